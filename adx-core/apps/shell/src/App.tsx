@@ -11,6 +11,7 @@ import Navigation from './components/Navigation';
 import Dashboard from './components/Dashboard';
 import ErrorBoundary from './components/ErrorBoundary';
 import MicroFrontendLoader from './components/MicroFrontendLoader';
+import { ModulePages } from './components/ModuleMicroFrontends';
 
 // Dynamic imports for micro-frontends
 const AuthApp = React.lazy(() => import('auth_app/App'));
@@ -51,6 +52,7 @@ const App: React.FC = () => {
                           <Route path="/users/*" element={<UserApp />} />
                           <Route path="/workflows/*" element={<WorkflowApp />} />
                           <Route path="/modules/*" element={<ModuleApp />} />
+                          <Route path="/apps/*" element={<ModulePages />} />
                           <Route path="*" element={<Navigate to="/" replace />} />
                         </Routes>
                       </Suspense>
@@ -0,0 +1,80 @@
+// Published theme of the domain the shell is served from, as returned by
+// white-label-service's public branding endpoint
+export interface Branding {
+  version: number;
+  brand_name: string;
+  logo_url?: string | null;
+  favicon_url?: string | null;
+  color_scheme: {
+    primary_color: string;
+    secondary_color: string;
+    accent_color: string;
+    background_color: string;
+    text_color: string;
+  };
+  typography: {
+    font_family: string;
+    heading_font?: string | null;
+    font_sizes: {
+      small: string;
+      medium: string;
+      large: string;
+      extra_large: string;
+    };
+  };
+  custom_css?: string | null;
+  published_at?: string | null;
+}
+
+const API_BASE_URL = import.meta.env.VITE_API_GATEWAY_URL ?? '';
+
+// Domains without a published theme get null and keep the default look
+export async function fetchBranding(domain: string): Promise<Branding | null> {
+  const response = await fetch(`${API_BASE_URL}/api/v1/branding/${encodeURIComponent(domain)}`);
+  if (response.status === 404) {
+    return null;
+  }
+  if (!response.ok) {
+    throw new Error(`Failed to load branding: ${response.statusText}`);
+  }
+  return response.json();
+}
+
+export function applyBranding(branding: Branding) {
+  const root = document.documentElement;
+  const { color_scheme: colors, typography } = branding;
+
+  root.style.setProperty('--brand-primary', colors.primary_color);
+  root.style.setProperty('--brand-secondary', colors.secondary_color);
+  root.style.setProperty('--brand-accent', colors.accent_color);
+  root.style.setProperty('--brand-background', colors.background_color);
+  root.style.setProperty('--brand-text', colors.text_color);
+  root.style.setProperty('--brand-font', typography.font_family);
+  root.style.setProperty('--brand-heading-font', typography.heading_font ?? typography.font_family);
+  root.style.setProperty('--brand-font-size-sm', typography.font_sizes.small);
+  root.style.setProperty('--brand-font-size-md', typography.font_sizes.medium);
+  root.style.setProperty('--brand-font-size-lg', typography.font_sizes.large);
+  root.style.setProperty('--brand-font-size-xl', typography.font_sizes.extra_large);
+  if (branding.logo_url) {
+    root.style.setProperty('--brand-logo', `url("${API_BASE_URL}${branding.logo_url}")`);
+  }
+
+  document.title = branding.brand_name;
+
+  if (branding.favicon_url) {
+    let favicon = document.querySelector<HTMLLinkElement>('link[rel="icon"]');
+    if (!favicon) {
+      favicon = document.createElement('link');
+      favicon.rel = 'icon';
+      document.head.appendChild(favicon);
+    }
+    favicon.href = `${API_BASE_URL}${branding.favicon_url}`;
+  }
+
+  if (branding.custom_css) {
+    const style = document.createElement('style');
+    style.dataset.branding = String(branding.version);
+    style.textContent = branding.custom_css;
+    document.head.appendChild(style);
+  }
+}
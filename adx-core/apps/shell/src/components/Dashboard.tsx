@@ -11,6 +11,8 @@ import {
   ActivityIcon
 } from 'lucide-react';
 
+import { ModuleSlot } from './ModuleMicroFrontends';
+
 const Dashboard: React.FC = () => {
   const { t } = useTranslation();
   const { currentTenant } = useTenantContext();
@@ -155,6 +157,11 @@ const Dashboard: React.FC = () => {
           </div>
         </div>
 
+        {/* Widgets of the tenant's modules */}
+        <div className="grid grid-cols-1 lg:grid-cols-2 gap-6 mb-8">
+          <ModuleSlot slot="dashboard" />
+        </div>
+
         {/* Recent Activity */}
         <div>
           <h2 className="text-xl font-semibold text-gray-900 dark:text-white mb-4">
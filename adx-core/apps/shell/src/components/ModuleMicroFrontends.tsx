@@ -0,0 +1,90 @@
+import React, { Suspense, useMemo } from 'react';
+import { Routes, Route } from 'react-router-dom';
+import { useQuery } from '@tanstack/react-query';
+import { useAuthStore, useTenantContext } from '@adx-core/shared-context';
+
+import ErrorBoundary from './ErrorBoundary';
+import MicroFrontendLoader from './MicroFrontendLoader';
+import {
+  fetchFrontendManifest,
+  lazyMicroFrontend,
+  FrontendModule,
+  MicroFrontend,
+} from '../modules/moduleFrontends';
+
+interface MountedMicroFrontend {
+  key: string;
+  microFrontend: MicroFrontend;
+  Component: React.LazyExoticComponent<React.ComponentType<any>>;
+}
+
+// Micro-frontends of the current tenant's modules, refetched when the
+// tenant changes
+export function useModuleFrontends(slot?: string): MountedMicroFrontend[] {
+  const { currentTenant } = useTenantContext();
+  const token = useAuthStore((state) => state.token);
+  const tenantId = currentTenant?.id;
+
+  const { data } = useQuery({
+    queryKey: ['module-frontends', tenantId],
+    queryFn: () => fetchFrontendManifest(tenantId as string, token),
+    enabled: Boolean(tenantId),
+    staleTime: 60 * 1000,
+  });
+
+  return useMemo(() => {
+    const modules: FrontendModule[] = data?.modules ?? [];
+    return modules.flatMap((module) =>
+      module.micro_frontends
+        .filter((microFrontend) => !slot || microFrontend.slot === slot)
+        .map((microFrontend) => ({
+          key: `${module.module_id}/${microFrontend.name}`,
+          microFrontend,
+          Component: lazyMicroFrontend(module, microFrontend),
+        }))
+    );
+  }, [data, slot]);
+}
+
+// Pages of the tenant's modules, mounted under /apps
+export const ModulePages: React.FC = () => {
+  const pages = useModuleFrontends('page');
+
+  return (
+    <Routes>
+      {pages
+        .filter(({ microFrontend }) => microFrontend.route)
+        .map(({ key, microFrontend, Component }) => (
+          <Route
+            key={key}
+            path={`${microFrontend.route!.replace(/^\/+/, '')}/*`}
+            element={
+              <ErrorBoundary>
+                <Suspense fallback={<MicroFrontendLoader />}>
+                  <Component />
+                </Suspense>
+              </ErrorBoundary>
+            }
+          />
+        ))}
+    </Routes>
+  );
+};
+
+// Every micro-frontend the tenant's modules mount in a shell slot, such as
+// "dashboard"
+export const ModuleSlot: React.FC<{ slot: string }> = ({ slot }) => {
+  const microFrontends = useModuleFrontends(slot);
+
+  return (
+    <>
+      {microFrontends.map(({ key, Component }) => (
+        <ErrorBoundary key={key}>
+          <Suspense fallback={<MicroFrontendLoader />}>
+            <Component />
+          </Suspense>
+        </ErrorBoundary>
+      ))}
+    </>
+  );
+};
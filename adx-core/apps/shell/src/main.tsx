@@ -1,10 +1,18 @@
 import React from 'react';
 import ReactDOM from 'react-dom/client';
 import App from './App.tsx';
+import { applyBranding, fetchBranding } from './branding';
 import './index.css';
 
-ReactDOM.createRoot(document.getElementById('root')!).render(
-  <React.StrictMode>
-    <App />
-  </React.StrictMode>,
-);
\ No newline at end of file
+// Brand the shell before the first render; without a theme it keeps the
+// default look
+fetchBranding(window.location.hostname)
+  .then((branding) => branding && applyBranding(branding))
+  .catch((error) => console.warn('Failed to load branding', error))
+  .finally(() => {
+    ReactDOM.createRoot(document.getElementById('root')!).render(
+      <React.StrictMode>
+        <App />
+      </React.StrictMode>,
+    );
+  });
@@ -0,0 +1,83 @@
+import React from 'react';
+
+// Micro-frontends of the current tenant's active modules, as listed by
+// module-service's frontend manifest
+export interface MicroFrontend {
+  name: string;
+  exposed_module: string;
+  slot: string;
+  route?: string | null;
+}
+
+export interface FrontendModule {
+  instance_id: string;
+  module_id: string;
+  version: string;
+  scope: string;
+  remote_entry_url: string;
+  integrity: string;
+  micro_frontends: MicroFrontend[];
+}
+
+export interface FrontendManifest {
+  tenant_id: string;
+  modules: FrontendModule[];
+  generated_at: string;
+}
+
+interface RemoteContainer {
+  init: (shared: unknown) => Promise<void> | void;
+  get: (module: string) => Promise<() => { default: React.ComponentType<any> }>;
+}
+
+const API_BASE_URL = import.meta.env.VITE_API_GATEWAY_URL ?? '';
+
+export async function fetchFrontendManifest(tenantId: string, authToken?: string | null): Promise<FrontendManifest> {
+  const response = await fetch(`${API_BASE_URL}/api/v1/modules/frontend-manifest/${encodeURIComponent(tenantId)}`, {
+    headers: {
+      ...(authToken ? { Authorization: `Bearer ${authToken}` } : {}),
+      'X-Tenant-ID': tenantId,
+    },
+  });
+
+  if (!response.ok) {
+    throw new Error(`Failed to load module frontends: ${response.statusText}`);
+  }
+
+  const result = await response.json();
+  return result.data;
+}
+
+const containers = new Map<string, Promise<RemoteContainer>>();
+
+// Remote entries are versioned and immutable, so each is loaded once. The
+// modulepreload link makes the browser check the entry's integrity before
+// the import below uses it.
+function loadContainer(module: FrontendModule): Promise<RemoteContainer> {
+  const url = `${API_BASE_URL}${module.remote_entry_url}`;
+  let container = containers.get(url);
+  if (!container) {
+    const preload = document.createElement('link');
+    preload.rel = 'modulepreload';
+    preload.href = url;
+    preload.integrity = module.integrity;
+    preload.crossOrigin = 'anonymous';
+    document.head.appendChild(preload);
+
+    container = import(/* @vite-ignore */ url).then(async (remote: RemoteContainer) => {
+      await remote.init({});
+      return remote;
+    });
+    container.catch(() => containers.delete(url));
+    containers.set(url, container);
+  }
+  return container;
+}
+
+export function lazyMicroFrontend(module: FrontendModule, microFrontend: MicroFrontend) {
+  return React.lazy(async () => {
+    const container = await loadContainer(module);
+    const factory = await container.get(microFrontend.exposed_module);
+    return factory();
+  });
+}
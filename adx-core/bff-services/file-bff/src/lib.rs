@@ -3,6 +3,7 @@ pub mod routes;
 pub mod services;
 pub mod types;
 
+pub use adx_bff_shared::ProgressHub;
 pub use services::{api_client::ApiClient, redis::RedisService};
 pub use types::*;
 
@@ -10,4 +11,5 @@ pub use types::*;
 pub struct AppState {
     pub api_client: ApiClient,
     pub redis: RedisService,
+    pub progress: ProgressHub,
 }
\ No newline at end of file
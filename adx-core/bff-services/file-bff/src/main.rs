@@ -23,12 +23,14 @@ mod types;
 
 use middleware::{auth::auth_middleware, error_handler::handle_error, tenant::tenant_middleware};
 use routes::{aggregated, files, workflows};
+use adx_bff_shared::ProgressHub;
 use services::{api_client::ApiClient, redis::RedisService};
 
 #[derive(Clone)]
 pub struct AppState {
     pub api_client: ApiClient,
     pub redis: RedisService,
+    pub progress: ProgressHub,
 }
 
 #[tokio::main]
@@ -48,8 +50,9 @@ async fn main() -> Result<()> {
     // Initialize services
     let api_client = ApiClient::new().await?;
     let redis = RedisService::new().await?;
+    let progress = ProgressHub::from_env()?;
 
-    let state = AppState { api_client, redis };
+    let state = AppState { api_client, redis, progress };
 
     // Build the application router
     let app = create_app(state);
@@ -117,7 +120,8 @@ mod tests {
     async fn test_health_check() {
         let api_client = ApiClient::new().await.unwrap();
         let redis = RedisService::new().await.unwrap();
-        let state = AppState { api_client, redis };
+        let progress = ProgressHub::from_env().unwrap();
+        let state = AppState { api_client, redis, progress };
         
         let app = create_app(state);
         let server = TestServer::new(app).unwrap();
@@ -1,9 +1,10 @@
 use axum::{
     extract::{Request, State},
-    http::{HeaderMap, StatusCode},
+    http::StatusCode,
     middleware::Next,
     response::Response,
 };
+use adx_bff_shared::request_token;
 use jsonwebtoken::{decode, DecodingKey, Validation, Algorithm};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -47,10 +48,9 @@ pub async fn auth_middleware(
         return Ok(next.run(request).await);
     }
 
-    let headers = request.headers();
-    
-    // Extract JWT token from Authorization header
-    let token = extract_token_from_headers(headers)
+    // Extract JWT token from Authorization header, or the query string on
+    // progress streams
+    let token = request_token(request.headers(), request.uri())
         .ok_or(StatusCode::UNAUTHORIZED)?;
 
     // Validate and decode JWT token
@@ -72,17 +72,6 @@ pub async fn auth_middleware(
     Ok(next.run(request).await)
 }
 
-fn extract_token_from_headers(headers: &HeaderMap) -> Option<String> {
-    let auth_header = headers.get("authorization")?;
-    let auth_str = auth_header.to_str().ok()?;
-    
-    if auth_str.starts_with("Bearer ") {
-        Some(auth_str[7..].to_string())
-    } else {
-        None
-    }
-}
-
 fn validate_jwt_token(token: &str) -> Result<Claims, jsonwebtoken::errors::Error> {
     // In production, this should use a proper JWT secret or public key
     let jwt_secret = std::env::var("JWT_SECRET")
@@ -1,6 +1,7 @@
+use adx_bff_shared::{progress_response, request_token};
 use axum::{
-    extract::{Path, Query, Request, State},
-    response::Json,
+    extract::{ws::WebSocketUpgrade, Path, Query, Request, State},
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
@@ -12,7 +13,7 @@ use crate::{
     middleware::{
         auth::Claims,
         error_handler::{BffError, BffResult},
-        tenant::get_tenant_context,
+        tenant::{get_tenant_context, get_tenant_id},
     },
     types::{
         BulkFileOperationWorkflowInput, FileCleanupWorkflowInput, FileMigrationWorkflowInput,
@@ -321,29 +322,26 @@ async fn cancel_workflow(
     Ok(Json(cancel_result))
 }
 
+/// Progress of a long-running operation, e.g. a bulk archive, pushed as
+/// server-sent events, or over a WebSocket when the client upgrades, until
+/// it finishes
 async fn stream_workflow_progress(
     State(state): State<AppState>,
     Path(operation_id): Path<String>,
+    upgrade: Option<WebSocketUpgrade>,
     request: Request,
-) -> BffResult<Json<serde_json::Value>> {
-    let tenant_context = get_tenant_context(&request)
+) -> BffResult<Response> {
+    let tenant_id = get_tenant_id(&request)
         .ok_or_else(|| BffError::tenant_validation("Missing tenant context"))?;
+    let token = request_token(request.headers(), request.uri())
+        .ok_or_else(|| BffError::authentication("Missing authorization"))?;
 
-    debug!("Streaming workflow progress for: {} for tenant: {}", operation_id, tenant_context.tenant_id);
+    debug!("Streaming workflow progress for: {} for tenant: {}", operation_id, tenant_id);
 
-    // For now, return the current status. In a full implementation, this would
-    // establish a WebSocket connection or Server-Sent Events stream
-    let workflow_status = state
-        .api_client
-        .get_workflow_status(&operation_id, &tenant_context.tenant_id, &get_auth_token(&request)?)
-        .await
-        .map_err(BffError::from)?;
-
-    Ok(Json(json!({
-        "operation_id": operation_id,
-        "stream_type": "status",
-        "data": workflow_status
-    })))
+    match state.progress.subscribe(&operation_id, &tenant_id, &token).await {
+        Ok(subscription) => Ok(progress_response(upgrade, subscription)),
+        Err(e) => Ok(e.into_response()),
+    }
 }
 
 // Validation functions
@@ -0,0 +1,7 @@
+//! Components shared by the Rust BFF services.
+
+pub mod progress;
+pub mod stream;
+
+pub use progress::{ProgressConfig, ProgressError, ProgressHub, ProgressSubscription, ProgressUpdate};
+pub use stream::{progress_response, request_token};
@@ -0,0 +1,490 @@
+//! Workflow progress pushed to BFF clients.
+//!
+//! Long-running operations such as bulk file operations and archive
+//! creation used to leave clients polling `/workflows/:id/status`. A BFF
+//! subscribes its client to the workflow instead: the hub watches the
+//! workflow's status at the API gateway once, however many clients are
+//! subscribed, and fans every change out to them until it finishes.
+
+use axum::{
+    http::StatusCode,
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use futures::{stream, Stream};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast::{self, error::RecvError};
+use tokio::time::MissedTickBehavior;
+use tracing::{debug, warn};
+
+/// Failed status lookups in a row before a watch gives up
+const MAX_CONSECUTIVE_FAILURES: u32 = 5;
+
+/// Updates kept for a subscriber that falls behind; older ones are skipped,
+/// as every update carries the workflow's whole state
+const CHANNEL_CAPACITY: usize = 64;
+
+#[derive(Debug, Clone)]
+pub struct ProgressConfig {
+    pub api_gateway_url: String,
+    /// How often a watched workflow's status is looked up
+    pub poll_interval: Duration,
+    /// Longest a workflow is watched; its subscribers are then told to fall
+    /// back to polling
+    pub max_watch: Duration,
+}
+
+impl ProgressConfig {
+    pub fn from_env() -> Self {
+        let env_u64 = |name: &str, default: u64| {
+            std::env::var(name)
+                .ok()
+                .and_then(|value| value.parse().ok())
+                .unwrap_or(default)
+        };
+
+        Self {
+            api_gateway_url: std::env::var("API_GATEWAY_URL")
+                .unwrap_or_else(|_| "http://localhost:8080".to_string()),
+            poll_interval: Duration::from_millis(env_u64("WORKFLOW_PROGRESS_POLL_MS", 1000).max(100)),
+            max_watch: Duration::from_secs(env_u64("WORKFLOW_PROGRESS_MAX_WATCH_SECONDS", 3600)),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UpdateKind {
+    WorkflowUpdate,
+    /// The BFF stopped watching before the workflow finished; clients
+    /// should poll its status from here on
+    WatchEnded,
+}
+
+/// A workflow's state as sent to subscribers
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ProgressUpdate {
+    #[serde(rename = "type")]
+    pub kind: UpdateKind,
+    pub workflow_id: String,
+    pub status: String,
+    pub progress: Option<Value>,
+    pub result: Option<Value>,
+    pub error: Option<Value>,
+    pub timestamp: DateTime<Utc>,
+}
+
+impl ProgressUpdate {
+    pub fn new(workflow_id: &str, status: &str) -> Self {
+        Self {
+            kind: UpdateKind::WorkflowUpdate,
+            workflow_id: workflow_id.to_string(),
+            status: status.to_string(),
+            progress: None,
+            result: None,
+            error: None,
+            timestamp: Utc::now(),
+        }
+    }
+
+    /// From the API gateway's workflow status response
+    pub fn from_status(workflow_id: &str, status: &Value) -> Self {
+        let field = |name: &str| status.get(name).filter(|value| !value.is_null()).cloned();
+        Self {
+            progress: field("progress"),
+            result: field("result"),
+            error: field("error"),
+            ..Self::new(workflow_id, status.get("status").and_then(Value::as_str).unwrap_or("unknown"))
+        }
+    }
+
+    fn watch_ended(workflow_id: &str, status: &str, reason: &str) -> Self {
+        Self {
+            kind: UpdateKind::WatchEnded,
+            error: Some(Value::String(reason.to_string())),
+            ..Self::new(workflow_id, status)
+        }
+    }
+
+    /// Whether no further updates follow this one
+    pub fn is_final(&self) -> bool {
+        self.kind == UpdateKind::WatchEnded || is_terminal_status(&self.status)
+    }
+
+    /// Whether anything but the time differs
+    fn same_state(&self, other: &Self) -> bool {
+        self.kind == other.kind
+            && self.status == other.status
+            && self.progress == other.progress
+            && self.result == other.result
+            && self.error == other.error
+    }
+}
+
+/// Services report statuses in either case, e.g. `completed` or `COMPLETED`
+fn is_terminal_status(status: &str) -> bool {
+    matches!(
+        status.to_ascii_lowercase().as_str(),
+        "completed" | "failed" | "cancelled" | "canceled" | "terminated" | "timedout" | "timed_out"
+    )
+}
+
+#[derive(Debug, thiserror::Error)]
+pub enum ProgressError {
+    #[error("Invalid workflow id: {0}")]
+    InvalidWorkflowId(String),
+
+    /// The gateway refused the lookup, e.g. the workflow doesn't exist or
+    /// the caller may not see it
+    #[error("Workflow status lookup returned {status}")]
+    Upstream { status: u16, body: Value },
+
+    #[error("API gateway unavailable: {0}")]
+    Unavailable(#[from] reqwest::Error),
+}
+
+impl ProgressError {
+    pub fn status_code(&self) -> StatusCode {
+        match self {
+            ProgressError::InvalidWorkflowId(_) => StatusCode::BAD_REQUEST,
+            ProgressError::Upstream { status, .. } => StatusCode::from_u16(*status)
+                .ok()
+                .filter(|status| status.is_client_error())
+                .unwrap_or(StatusCode::BAD_GATEWAY),
+            ProgressError::Unavailable(_) => StatusCode::BAD_GATEWAY,
+        }
+    }
+}
+
+impl IntoResponse for ProgressError {
+    fn into_response(self) -> Response {
+        let status = self.status_code();
+        // The gateway's own error body tells the client more than ours would
+        let body = match self {
+            ProgressError::Upstream { body, .. } if status.is_client_error() && !body.is_null() => body,
+            other => serde_json::json!({
+                "error": "WORKFLOW_PROGRESS_UNAVAILABLE",
+                "message": other.to_string(),
+                "details": null
+            }),
+        };
+        (status, Json(body)).into_response()
+    }
+}
+
+/// A client's view of one workflow: its state when subscribing, then every
+/// change until it finishes
+pub struct ProgressSubscription {
+    pub initial: ProgressUpdate,
+    /// None when the workflow had already finished
+    updates: Option<broadcast::Receiver<ProgressUpdate>>,
+}
+
+impl ProgressSubscription {
+    /// Updates in order, starting with the current state and ending with
+    /// the final one
+    pub fn into_stream(self) -> impl Stream<Item = ProgressUpdate> + Send + 'static {
+        stream::unfold((Some(self.initial), self.updates), |(pending, updates)| async move {
+            if let Some(update) = pending {
+                let updates = if update.is_final() { None } else { updates };
+                return Some((update, (None, updates)));
+            }
+
+            let mut receiver = updates?;
+            loop {
+                match receiver.recv().await {
+                    Ok(update) => {
+                        let rest = if update.is_final() { None } else { Some(receiver) };
+                        return Some((update, (None, rest)));
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        debug!(skipped = skipped, "Progress subscriber fell behind, skipping to the latest update");
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+struct WatchKey {
+    tenant_id: String,
+    workflow_id: String,
+}
+
+struct Watch {
+    sender: broadcast::Sender<ProgressUpdate>,
+    /// Token of the latest subscriber, so the watch outlives the first
+    /// subscriber's session
+    token: String,
+}
+
+/// Watches workflows for the clients subscribed to them
+#[derive(Clone)]
+pub struct ProgressHub {
+    client: reqwest::Client,
+    config: Arc<ProgressConfig>,
+    watches: Arc<Mutex<HashMap<WatchKey, Watch>>>,
+}
+
+impl ProgressHub {
+    pub fn new(config: ProgressConfig) -> anyhow::Result<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(10))
+            .build()?;
+
+        Ok(Self {
+            client,
+            config: Arc::new(config),
+            watches: Arc::new(Mutex::new(HashMap::new())),
+        })
+    }
+
+    pub fn from_env() -> anyhow::Result<Self> {
+        Self::new(ProgressConfig::from_env())
+    }
+
+    /// Subscribe a client to a workflow. The first lookup uses the client's
+    /// own token, so it only gets workflows the gateway lets it see.
+    pub async fn subscribe(
+        &self,
+        workflow_id: &str,
+        tenant_id: &str,
+        token: &str,
+    ) -> Result<ProgressSubscription, ProgressError> {
+        let initial = self.fetch(workflow_id, tenant_id, token).await?;
+        if initial.is_final() {
+            return Ok(ProgressSubscription { initial, updates: None });
+        }
+
+        let key = WatchKey {
+            tenant_id: tenant_id.to_string(),
+            workflow_id: workflow_id.to_string(),
+        };
+        let mut watches = self.watches.lock().unwrap_or_else(|e| e.into_inner());
+        let receiver = match watches.get_mut(&key) {
+            Some(watch) => {
+                watch.token = token.to_string();
+                watch.sender.subscribe()
+            }
+            None => {
+                let (sender, receiver) = broadcast::channel(CHANNEL_CAPACITY);
+                watches.insert(key.clone(), Watch { sender: sender.clone(), token: token.to_string() });
+                debug!(workflow_id = %workflow_id, tenant_id = %tenant_id, "Watching workflow progress");
+                tokio::spawn(self.clone().watch(key, sender, initial.clone()));
+                receiver
+            }
+        };
+
+        Ok(ProgressSubscription { initial, updates: Some(receiver) })
+    }
+
+    /// Push an update the BFF learned of itself, e.g. right after cancelling
+    /// a workflow; returns how many subscribers it reached
+    pub fn publish(&self, tenant_id: &str, update: ProgressUpdate) -> usize {
+        let key = WatchKey {
+            tenant_id: tenant_id.to_string(),
+            workflow_id: update.workflow_id.clone(),
+        };
+        let watches = self.watches.lock().unwrap_or_else(|e| e.into_inner());
+        watches
+            .get(&key)
+            .and_then(|watch| watch.sender.send(update).ok())
+            .unwrap_or(0)
+    }
+
+    /// Workflows being watched, for health and metrics endpoints
+    pub fn watched_workflows(&self) -> usize {
+        self.watches.lock().unwrap_or_else(|e| e.into_inner()).len()
+    }
+
+    async fn watch(self, key: WatchKey, sender: broadcast::Sender<ProgressUpdate>, mut last: ProgressUpdate) {
+        let started = Instant::now();
+        let mut failures = 0;
+        let mut ticker = tokio::time::interval(self.config.poll_interval);
+        ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+        // The first tick is immediate, and the subscriber already has the
+        // current state
+        ticker.tick().await;
+
+        loop {
+            ticker.tick().await;
+
+            let token = {
+                let mut watches = self.watches.lock().unwrap_or_else(|e| e.into_inner());
+                // Checked under the lock, so nobody subscribes to a watch
+                // that is about to stop
+                if sender.receiver_count() == 0 {
+                    watches.remove(&key);
+                    debug!(workflow_id = %key.workflow_id, "No subscribers left, no longer watching workflow");
+                    return;
+                }
+                match watches.get(&key) {
+                    Some(watch) => watch.token.clone(),
+                    None => return,
+                }
+            };
+
+            if started.elapsed() >= self.config.max_watch {
+                self.finish(&key, &sender, ProgressUpdate::watch_ended(
+                    &key.workflow_id,
+                    &last.status,
+                    "Workflow watched for too long, poll its status instead",
+                ));
+                return;
+            }
+
+            match self.fetch(&key.workflow_id, &key.tenant_id, &token).await {
+                Ok(update) => {
+                    failures = 0;
+                    if update.is_final() {
+                        self.finish(&key, &sender, update);
+                        return;
+                    }
+                    if !update.same_state(&last) {
+                        let _ = sender.send(update.clone());
+                        last = update;
+                    }
+                }
+                Err(e) => {
+                    failures += 1;
+                    warn!(workflow_id = %key.workflow_id, failures = failures, error = %e, "Workflow status lookup failed");
+                    if failures >= MAX_CONSECUTIVE_FAILURES {
+                        self.finish(&key, &sender, ProgressUpdate::watch_ended(
+                            &key.workflow_id,
+                            &last.status,
+                            "Workflow status is unavailable, poll its status instead",
+                        ));
+                        return;
+                    }
+                }
+            }
+        }
+    }
+
+    /// Stop watching, then send the last update. Whoever subscribes after
+    /// the watch is gone starts a new one and sees the final state in its
+    /// first lookup.
+    fn finish(&self, key: &WatchKey, sender: &broadcast::Sender<ProgressUpdate>, update: ProgressUpdate) {
+        self.watches.lock().unwrap_or_else(|e| e.into_inner()).remove(key);
+        debug!(workflow_id = %key.workflow_id, status = %update.status, "Finished watching workflow");
+        let _ = sender.send(update);
+    }
+
+    async fn fetch(&self, workflow_id: &str, tenant_id: &str, token: &str) -> Result<ProgressUpdate, ProgressError> {
+        if !is_valid_workflow_id(workflow_id) {
+            return Err(ProgressError::InvalidWorkflowId(workflow_id.to_string()));
+        }
+
+        let url = format!(
+            "{}/api/v1/workflows/{}/status",
+            self.config.api_gateway_url.trim_end_matches('/'),
+            workflow_id
+        );
+        let response = self
+            .client
+            .get(&url)
+            .bearer_auth(token)
+            .header("X-Tenant-ID", tenant_id)
+            .send()
+            .await?;
+
+        let status = response.status();
+        if !status.is_success() {
+            let body = response.json::<Value>().await.unwrap_or(Value::Null);
+            return Err(ProgressError::Upstream { status: status.as_u16(), body });
+        }
+
+        let body = response.json::<Value>().await?;
+        Ok(ProgressUpdate::from_status(workflow_id, &body))
+    }
+}
+
+/// Workflow ids go into the gateway URL as a path segment
+fn is_valid_workflow_id(workflow_id: &str) -> bool {
+    !workflow_id.is_empty()
+        && workflow_id.len() <= 255
+        && workflow_id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.' | ':'))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures::StreamExt;
+    use serde_json::json;
+
+    fn hub() -> ProgressHub {
+        ProgressHub::new(ProgressConfig {
+            api_gateway_url: "http://localhost:8080".to_string(),
+            poll_interval: Duration::from_secs(1),
+            max_watch: Duration::from_secs(60),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_update_from_gateway_status() {
+        let update = ProgressUpdate::from_status("bulk-file-op-1", &json!({
+            "operation_id": "bulk-file-op-1",
+            "status": "running",
+            "progress": { "current_step": "archiving", "completed_steps": 2, "total_steps": 5, "percentage": 40.0 },
+            "result": null,
+            "error": null
+        }));
+
+        assert_eq!(update.kind, UpdateKind::WorkflowUpdate);
+        assert_eq!(update.status, "running");
+        assert_eq!(update.progress.unwrap()["percentage"], json!(40.0));
+        assert!(update.result.is_none());
+        assert!(!ProgressUpdate::new("wf-1", "running").is_final());
+        assert!(ProgressUpdate::new("wf-1", "COMPLETED").is_final());
+        assert!(ProgressUpdate::new("wf-1", "timedout").is_final());
+        assert!(ProgressUpdate::watch_ended("wf-1", "running", "gave up").is_final());
+    }
+
+    #[test]
+    fn test_workflow_id_validation() {
+        assert!(is_valid_workflow_id("user-sync-user-1-6f1c"));
+        assert!(is_valid_workflow_id("bulk_file_operation_workflow:42"));
+        assert!(!is_valid_workflow_id(""));
+        assert!(!is_valid_workflow_id("../admin"));
+        assert!(!is_valid_workflow_id("wf-1?status=completed"));
+    }
+
+    #[tokio::test]
+    async fn test_stream_ends_with_final_update() {
+        let (sender, receiver) = broadcast::channel(CHANNEL_CAPACITY);
+        let subscription = ProgressSubscription {
+            initial: ProgressUpdate::new("wf-1", "running"),
+            updates: Some(receiver),
+        };
+        sender.send(ProgressUpdate::new("wf-1", "running")).unwrap();
+        sender.send(ProgressUpdate::new("wf-1", "completed")).unwrap();
+        sender.send(ProgressUpdate::new("wf-1", "running")).unwrap();
+
+        let statuses: Vec<String> = subscription.into_stream().map(|update| update.status).collect().await;
+        assert_eq!(statuses, vec!["running", "running", "completed"]);
+    }
+
+    #[tokio::test]
+    async fn test_publish_reaches_only_the_tenants_watch() {
+        let hub = hub();
+        let (sender, mut receiver) = broadcast::channel(CHANNEL_CAPACITY);
+        hub.watches.lock().unwrap().insert(
+            WatchKey { tenant_id: "tenant-1".to_string(), workflow_id: "wf-1".to_string() },
+            Watch { sender, token: "token".to_string() },
+        );
+
+        assert_eq!(hub.publish("tenant-2", ProgressUpdate::new("wf-1", "cancelled")), 0);
+        assert_eq!(hub.publish("tenant-1", ProgressUpdate::new("wf-1", "cancelled")), 1);
+        assert_eq!(receiver.recv().await.unwrap().status, "cancelled");
+    }
+}
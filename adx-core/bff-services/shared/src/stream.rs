@@ -0,0 +1,108 @@
+//! Serving progress subscriptions as server-sent events or over a WebSocket.
+
+use axum::{
+    extract::ws::{Message, WebSocket, WebSocketUpgrade},
+    http::{HeaderMap, Uri},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Response,
+    },
+};
+use futures::StreamExt;
+use tracing::debug;
+
+use crate::progress::{ProgressSubscription, UpdateKind};
+
+/// Path suffix of the routes that stream progress
+pub const STREAM_PATH_SUFFIX: &str = "/stream";
+
+/// A WebSocket when the client asked to upgrade, server-sent events
+/// otherwise. Either way the current state comes first and the stream ends
+/// after the final update.
+pub fn progress_response(upgrade: Option<WebSocketUpgrade>, subscription: ProgressSubscription) -> Response {
+    match upgrade {
+        Some(upgrade) => upgrade.on_upgrade(move |socket| serve_websocket(socket, subscription)),
+        None => {
+            let events = subscription.into_stream().map(|update| {
+                let name = match update.kind {
+                    UpdateKind::WorkflowUpdate => "workflow_update",
+                    UpdateKind::WatchEnded => "watch_ended",
+                };
+                Event::default().event(name).json_data(&update)
+            });
+            Sse::new(events).keep_alive(KeepAlive::default()).into_response()
+        }
+    }
+}
+
+async fn serve_websocket(mut socket: WebSocket, subscription: ProgressSubscription) {
+    let mut updates = Box::pin(subscription.into_stream());
+    loop {
+        tokio::select! {
+            update = updates.next() => match update {
+                Some(update) => {
+                    let Ok(text) = serde_json::to_string(&update) else {
+                        continue;
+                    };
+                    if socket.send(Message::Text(text)).await.is_err() {
+                        return;
+                    }
+                }
+                None => {
+                    let _ = socket.send(Message::Close(None)).await;
+                    return;
+                }
+            },
+            // Clients only ever close; pings are answered by the socket itself
+            incoming = socket.recv() => match incoming {
+                Some(Ok(Message::Close(_))) | Some(Err(_)) | None => {
+                    debug!("Progress WebSocket closed by client");
+                    return;
+                }
+                Some(Ok(_)) => {}
+            },
+        }
+    }
+}
+
+/// The caller's bearer token. Browsers can't set headers on `EventSource`
+/// requests or WebSocket upgrades, so on stream routes it may come in the
+/// `access_token` query parameter instead.
+pub fn request_token(headers: &HeaderMap, uri: &Uri) -> Option<String> {
+    headers
+        .get("authorization")
+        .and_then(|header| header.to_str().ok())
+        .and_then(|header| header.strip_prefix("Bearer "))
+        .or_else(|| query_token(uri))
+        .map(str::to_string)
+}
+
+fn query_token(uri: &Uri) -> Option<&str> {
+    if !uri.path().ends_with(STREAM_PATH_SUFFIX) {
+        return None;
+    }
+    uri.query()?
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("access_token="))
+        .filter(|token| !token.is_empty())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_query_token_only_on_stream_routes() {
+        let headers = HeaderMap::new();
+
+        let uri: Uri = "/api/workflows/wf-1/stream?access_token=abc.def.ghi".parse().unwrap();
+        assert_eq!(request_token(&headers, &uri).as_deref(), Some("abc.def.ghi"));
+
+        let uri: Uri = "/api/workflows/wf-1/status?access_token=abc.def.ghi".parse().unwrap();
+        assert_eq!(request_token(&headers, &uri), None);
+
+        let mut headers = HeaderMap::new();
+        headers.insert("authorization", "Bearer header.token".parse().unwrap());
+        assert_eq!(request_token(&headers, &uri).as_deref(), Some("header.token"));
+    }
+}
@@ -0,0 +1,39 @@
+import { describe, it, expect } from 'vitest';
+import { buildAccessBanner, BILLING_ACTION_URL } from '../services/accessBanner.js';
+
+describe('buildAccessBanner', () => {
+  const now = new Date('2024-03-01T12:00:00Z');
+
+  it('shows no banner at full access', () => {
+    expect(buildAccessBanner({ access_mode: 'full', grace_ends_at: null }, now)).toBeNull();
+  });
+
+  it('counts down the grace period while read-only', () => {
+    const banner = buildAccessBanner(
+      { access_mode: 'read_only', grace_ends_at: '2024-03-04T00:00:00Z' },
+      now
+    );
+
+    expect(banner).toMatchObject({
+      severity: 'warning',
+      accessMode: 'read_only',
+      graceEndsAt: '2024-03-04T00:00:00Z',
+      daysRemaining: 3,
+      actionUrl: BILLING_ACTION_URL,
+    });
+    expect(banner?.message).toContain('read-only for 3 more days');
+  });
+
+  it('tells suspended tenants their grace period is over', () => {
+    const banner = buildAccessBanner(
+      { access_mode: 'suspended', grace_ends_at: '2024-02-28T00:00:00Z' },
+      now
+    );
+
+    expect(banner).toMatchObject({
+      severity: 'error',
+      accessMode: 'suspended',
+      daysRemaining: 0,
+    });
+  });
+});
@@ -7,6 +7,7 @@ import { requireTenant, requireTenantAccess, requireTenantPermission } from '../
 import { createEndpointRateLimit } from '../middleware/rateLimit.js';
 import { asyncHandler } from '../middleware/errorHandler.js';
 import { AnalyticsPeriod } from '../types/tenant.js';
+import { buildAccessBanner } from '../services/accessBanner.js';
 
 export function createAggregatedRoutes(
   redisClient: RedisClient,
@@ -333,7 +334,14 @@ export function createAggregatedRoutes(
         return res.status(400).json({ error: 'Complete tenant context required' });
       }
 
+      // No banner rather than no header when license-service is unreachable
+      const accessBanner = await apiClient
+        .getTenantEntitlements(tenant.id, req.headers.authorization?.substring(7) || '')
+        .then(entitlements => buildAccessBanner(entitlements))
+        .catch(() => null);
+
       const quickStats = {
+        accessBanner,
         tenant: {
           id: tenant.id,
           name: tenant.name,
@@ -15,6 +15,7 @@ import {
   createEndpointRateLimit 
 } from '../middleware/rateLimit.js';
 import { asyncHandler } from '../middleware/errorHandler.js';
+import { buildAccessBanner } from '../services/accessBanner.js';
 import { 
   TenantSwitchRequestSchema, 
   AnalyticsPeriodSchema,
@@ -278,6 +279,84 @@ export function createTenantRoutes(
     })
   );
 
+  // Get the tenant's access mode and the banner to show for it
+  router.get(
+    '/:tenantId/access',
+    authMiddleware,
+    requireTenant,
+    requireTenantAccess,
+    requireTenantPermission('tenant:read'),
+    createEndpointRateLimit(redisClient, 'tenant-access', { maxRequests: 60 }),
+    asyncHandler(async (req: TenantRequest, res) => {
+      const { tenantId } = req.params;
+
+      const entitlements = await apiClient.getTenantEntitlements(
+        tenantId,
+        req.headers.authorization?.substring(7) || ''
+      );
+
+      res.json({
+        accessMode: entitlements.access_mode,
+        graceEndsAt: entitlements.grace_ends_at,
+        banner: buildAccessBanner(entitlements),
+        timestamp: new Date().toISOString(),
+      });
+    })
+  );
+
+  // List tenant invoices and receipts
+  router.get(
+    '/:tenantId/billing/documents',
+    authMiddleware,
+    requireTenant,
+    requireTenantAccess,
+    requireTenantPermission('tenant:billing:read'),
+    createEndpointRateLimit(redisClient, 'tenant-billing-documents', { maxRequests: 30 }),
+    asyncHandler(async (req: TenantRequest, res) => {
+      const { tenantId } = req.params;
+      const limit = Math.min(parseInt(req.query.limit as string) || 50, 100);
+      const offset = parseInt(req.query.offset as string) || 0;
+
+      const documents = await apiClient.getBillingDocuments(
+        tenantId,
+        req.headers.authorization?.substring(7) || '',
+        limit,
+        offset
+      );
+
+      res.json({
+        documents,
+        limit,
+        offset,
+        timestamp: new Date().toISOString(),
+      });
+    })
+  );
+
+  // Get a short-lived download link for an invoice or receipt
+  router.get(
+    '/:tenantId/billing/documents/:documentId/download',
+    authMiddleware,
+    requireTenant,
+    requireTenantAccess,
+    requireTenantPermission('tenant:billing:read'),
+    createEndpointRateLimit(redisClient, 'tenant-billing-document-download', { maxRequests: 30 }),
+    asyncHandler(async (req: TenantRequest, res) => {
+      const { tenantId, documentId } = req.params;
+
+      const download = await apiClient.getBillingDocumentDownload(
+        tenantId,
+        documentId,
+        req.headers.authorization?.substring(7) || ''
+      );
+
+      res.json({
+        ...download,
+        timestamp: new Date().toISOString(),
+      });
+    })
+  );
+
   // Get tenant configuration
   router.get(
     '/:tenantId/configuration',
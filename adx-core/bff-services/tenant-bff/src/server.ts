@@ -33,6 +33,7 @@ const apiClient = new ApiClient({
   userServiceUrl: process.env.USER_SERVICE_URL || 'http://localhost:8082',
   authServiceUrl: process.env.AUTH_SERVICE_URL || 'http://localhost:8081',
   workflowServiceUrl: process.env.WORKFLOW_SERVICE_URL || 'http://localhost:8084',
+  licenseServiceUrl: process.env.LICENSE_SERVICE_URL || 'http://localhost:8087',
   timeout: 10000,
 });
 
@@ -281,6 +282,7 @@ server.listen(port, () => {
   console.log(`👤 User Service: ${process.env.USER_SERVICE_URL || 'http://localhost:8082'}`);
   console.log(`🔐 Auth Service: ${process.env.AUTH_SERVICE_URL || 'http://localhost:8081'}`);
   console.log(`⚡ Workflow Service: ${process.env.WORKFLOW_SERVICE_URL || 'http://localhost:8084'}`);
+  console.log(`🧾 License Service: ${process.env.LICENSE_SERVICE_URL || 'http://localhost:8087'}`);
   console.log(`🗄️  Redis: ${process.env.REDIS_URL || 'redis://localhost:6379'}`);
 });
 
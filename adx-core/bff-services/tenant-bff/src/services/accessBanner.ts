@@ -0,0 +1,48 @@
+import { AccessBanner, TenantEntitlements } from '../types/tenant.js';
+
+const DAY_MS = 24 * 60 * 60 * 1000;
+
+// Where the banner sends tenant admins to settle their bill
+export const BILLING_ACTION_URL = '/settings/billing';
+
+// The banner for a tenant whose license lapsed, or null at full access.
+// api-gateway enforces the access mode; this only tells users about it.
+export function buildAccessBanner(
+  entitlements: Pick<TenantEntitlements, 'access_mode' | 'grace_ends_at'>,
+  now: Date = new Date()
+): AccessBanner | null {
+  const graceEndsAt = entitlements.grace_ends_at;
+
+  switch (entitlements.access_mode) {
+    case 'read_only': {
+      const daysRemaining = graceEndsAt
+        ? Math.max(0, Math.ceil((new Date(graceEndsAt).getTime() - now.getTime()) / DAY_MS))
+        : null;
+      const until = daysRemaining === null
+        ? ''
+        : daysRemaining === 1 ? ' for 1 more day' : ` for ${daysRemaining} more days`;
+
+      return {
+        severity: 'warning',
+        accessMode: 'read_only',
+        title: 'Your subscription has lapsed',
+        message: `Your workspace is read-only${until}. Renew your subscription to make changes again and avoid suspension.`,
+        graceEndsAt,
+        daysRemaining,
+        actionUrl: BILLING_ACTION_URL,
+      };
+    }
+    case 'suspended':
+      return {
+        severity: 'error',
+        accessMode: 'suspended',
+        title: 'Your workspace is suspended',
+        message: 'Your grace period has ended. Renew your subscription to restore access.',
+        graceEndsAt,
+        daysRemaining: graceEndsAt ? 0 : null,
+        actionUrl: BILLING_ACTION_URL,
+      };
+    default:
+      return null;
+  }
+}
@@ -6,7 +6,10 @@ import {
   TenantSwitchResult,
   TenantWorkflowRequest,
   WorkflowResponse,
-  AnalyticsPeriod
+  AnalyticsPeriod,
+  BillingDocument,
+  BillingDocumentDownload,
+  TenantEntitlements
 } from '../types/tenant.js';
 
 export interface ApiClientConfig {
@@ -15,6 +18,7 @@ export interface ApiClientConfig {
   userServiceUrl: string;
   authServiceUrl: string;
   workflowServiceUrl: string;
+  licenseServiceUrl: string;
   timeout: number;
 }
 
@@ -117,6 +121,49 @@ export class ApiClient {
     );
   }
 
+  // Billing documents, served by license-service
+  public async getBillingDocuments(
+    tenantId: string,
+    authToken: string,
+    limit: number = 50,
+    offset: number = 0
+  ): Promise<BillingDocument[]> {
+    const response = await this.makeRequest(
+      'GET',
+      `${this.config.licenseServiceUrl}/billing/tenant/${tenantId}/documents?limit=${limit}&offset=${offset}`,
+      undefined,
+      { Authorization: `Bearer ${authToken}`, 'X-Tenant-ID': tenantId }
+    );
+    return response.data;
+  }
+
+  public async getBillingDocumentDownload(
+    tenantId: string,
+    documentId: string,
+    authToken: string
+  ): Promise<BillingDocumentDownload> {
+    const response = await this.makeRequest(
+      'GET',
+      `${this.config.licenseServiceUrl}/billing/tenant/${tenantId}/documents/${documentId}/download`,
+      undefined,
+      { Authorization: `Bearer ${authToken}`, 'X-Tenant-ID': tenantId }
+    );
+    return response.data;
+  }
+
+  public async getTenantEntitlements(
+    tenantId: string,
+    authToken: string
+  ): Promise<TenantEntitlements> {
+    const response = await this.makeRequest(
+      'GET',
+      `${this.config.licenseServiceUrl}/api/v1/entitlements/${tenantId}`,
+      undefined,
+      { Authorization: `Bearer ${authToken}`, 'X-Tenant-ID': tenantId }
+    );
+    return response.data;
+  }
+
   // Workflow operations
   public async initiateWorkflow<T>(
     request: TenantWorkflowRequest,
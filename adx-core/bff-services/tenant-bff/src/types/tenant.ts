@@ -236,6 +236,53 @@ export enum WorkflowPriority {
   CRITICAL = 'critical',
 }
 
+// An invoice or receipt PDF, as listed by license-service
+export interface BillingDocument {
+  id: string;
+  tenant_id: string;
+  billing_history_id: string;
+  document_type: 'Invoice' | 'Receipt';
+  document_number: string;
+  file_id: string;
+  filename: string;
+  file_size: number;
+  amount: string;
+  currency: string;
+  issued_at: string;
+}
+
+export interface BillingDocumentDownload {
+  document_id: string;
+  filename: string;
+  download_url: string;
+  expires_at: string;
+}
+
+// How much of the platform a tenant can use while its license lapses
+export type TenantAccessMode = 'full' | 'read_only' | 'suspended';
+
+// The parts of a tenant's license-service entitlements the BFF uses
+export interface TenantEntitlements {
+  tenant_id: string;
+  subscription_tier: string | null;
+  license_status: string | null;
+  active: boolean;
+  features: string[];
+  access_mode: TenantAccessMode;
+  grace_ends_at: string | null;
+}
+
+// Shown across the top of the app while a tenant is not at full access
+export interface AccessBanner {
+  severity: 'warning' | 'error';
+  accessMode: TenantAccessMode;
+  title: string;
+  message: string;
+  graceEndsAt: string | null;
+  daysRemaining: number | null;
+  actionUrl: string;
+}
+
 export interface WorkflowResponse<T = any> {
   type: 'sync' | 'async';
   operationId?: string;
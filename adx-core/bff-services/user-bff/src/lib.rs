@@ -3,6 +3,7 @@ pub mod routes;
 pub mod services;
 pub mod types;
 
+pub use adx_bff_shared::ProgressHub;
 pub use services::{api_client::ApiClient, redis::RedisService, temporal_client::TemporalClient};
 pub use types::*;
 
@@ -11,4 +12,5 @@ pub struct AppState {
     pub api_client: ApiClient,
     pub redis: RedisService,
     pub temporal_client: TemporalClient,
+    pub progress: ProgressHub,
 }
\ No newline at end of file
@@ -18,6 +18,7 @@ mod types;
 
 use middleware::{auth::auth_middleware, error_handler::handle_error, tenant::tenant_middleware};
 use routes::{aggregated, users, workflows};
+use adx_bff_shared::ProgressHub;
 use services::{api_client::ApiClient, redis::RedisService, temporal_client::TemporalClient};
 
 #[derive(Clone)]
@@ -25,6 +26,7 @@ pub struct AppState {
     pub api_client: ApiClient,
     pub redis: RedisService,
     pub temporal_client: TemporalClient,
+    pub progress: ProgressHub,
 }
 
 #[tokio::main]
@@ -45,11 +47,13 @@ async fn main() -> Result<()> {
     let api_client = ApiClient::new().await?;
     let redis = RedisService::new().await?;
     let temporal_client = TemporalClient::new().await?;
+    let progress = ProgressHub::from_env()?;
 
     let state = AppState { 
         api_client, 
         redis, 
-        temporal_client 
+        temporal_client,
+        progress,
     };
 
     // Build the application router
@@ -106,7 +110,8 @@ mod tests {
         let api_client = ApiClient::new().await.unwrap();
         let redis = RedisService::new().await.unwrap();
         let temporal_client = TemporalClient::new().await.unwrap();
-        let state = AppState { api_client, redis, temporal_client };
+        let progress = ProgressHub::from_env().unwrap();
+        let state = AppState { api_client, redis, temporal_client, progress };
         
         let app = create_app(state);
         let server = TestServer::new(app).unwrap();
@@ -4,6 +4,7 @@ use axum::{
     middleware::Next,
     response::Response,
 };
+use adx_bff_shared::request_token;
 use jsonwebtoken::{decode, DecodingKey, Validation, Algorithm};
 use serde::{Deserialize, Serialize};
 
@@ -24,12 +25,8 @@ pub async fn auth_middleware(
     mut request: Request,
     next: Next,
 ) -> Result<Response, StatusCode> {
-    let auth_header = headers
-        .get("authorization")
-        .and_then(|header| header.to_str().ok())
-        .and_then(|header| header.strip_prefix("Bearer "));
-
-    let token = match auth_header {
+    // Progress streams may carry the token in the query string
+    let token = match request_token(&headers, request.uri()) {
         Some(token) => token,
         None => return Err(StatusCode::UNAUTHORIZED),
     };
@@ -40,7 +37,7 @@ pub async fn auth_middleware(
     
     let validation = Validation::new(Algorithm::HS256);
     let token_data = decode::<Claims>(
-        token,
+        &token,
         &DecodingKey::from_secret(jwt_secret.as_ref()),
         &validation,
     );
@@ -1,7 +1,8 @@
+use adx_bff_shared::{progress_response, request_token};
 use axum::{
-    extract::{Path, State, Extension},
-    http::StatusCode,
-    response::Json,
+    extract::{ws::WebSocketUpgrade, Path, State, Extension},
+    http::{HeaderMap, StatusCode, Uri},
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
@@ -14,6 +15,7 @@ pub fn create_routes() -> Router<AppState> {
         .route("/user/:user_id", get(get_user_workflows))
         .route("/user/:user_id/sync", post(start_user_sync))
         .route("/:workflow_id/status", get(get_workflow_status))
+        .route("/:workflow_id/stream", get(stream_workflow_progress))
 }
 
 async fn get_user_workflows(
@@ -53,7 +55,8 @@ async fn start_user_sync(
             "workflow_id": workflow_id,
             "status": "STARTED",
             "user_id": user_id,
-            "started_at": chrono::Utc::now().to_rfc3339()
+            "started_at": chrono::Utc::now().to_rfc3339(),
+            "stream_url": format!("/api/workflows/{}/stream", workflow_id)
         }))),
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
@@ -69,4 +72,25 @@ async fn get_workflow_status(
         Ok(status) => Ok(Json(json!(status))),
         Err(_) => Err(StatusCode::INTERNAL_SERVER_ERROR),
     }
-}
\ No newline at end of file
+}
+
+/// Progress pushed as server-sent events, or over a WebSocket when the
+/// client upgrades, until the workflow finishes
+async fn stream_workflow_progress(
+    State(state): State<AppState>,
+    Path(workflow_id): Path<String>,
+    Extension(_claims): Extension<Claims>,
+    Extension(tenant): Extension<TenantContext>,
+    headers: HeaderMap,
+    uri: Uri,
+    upgrade: Option<WebSocketUpgrade>,
+) -> Response {
+    let Some(token) = request_token(&headers, &uri) else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+
+    match state.progress.subscribe(&workflow_id, &tenant.tenant_id, &token).await {
+        Ok(subscription) => progress_response(upgrade, subscription),
+        Err(e) => e.into_response(),
+    }
+}
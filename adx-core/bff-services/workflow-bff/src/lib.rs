@@ -3,7 +3,8 @@ pub mod routes;
 pub mod services;
 pub mod types;
 
-pub use services::{api_client::ApiClient, redis::RedisService, temporal_client::TemporalClient, websocket::WebSocketService};
+pub use adx_bff_shared::ProgressHub;
+pub use services::{api_client::ApiClient, redis::RedisService, temporal_client::TemporalClient};
 pub use types::*;
 
 #[derive(Clone)]
@@ -11,5 +12,5 @@ pub struct AppState {
     pub api_client: ApiClient,
     pub redis: RedisService,
     pub temporal_client: TemporalClient,
-    pub websocket: WebSocketService,
+    pub progress: ProgressHub,
 }
\ No newline at end of file
@@ -23,14 +23,15 @@ mod types;
 
 use middleware::{auth::auth_middleware, error_handler::handle_error, tenant::tenant_middleware};
 use routes::{aggregated, monitoring, workflows};
-use services::{api_client::ApiClient, redis::RedisService, temporal_client::TemporalClient, websocket::WebSocketService};
+use adx_bff_shared::ProgressHub;
+use services::{api_client::ApiClient, redis::RedisService, temporal_client::TemporalClient};
 
 #[derive(Clone)]
 pub struct AppState {
     pub api_client: ApiClient,
     pub redis: RedisService,
     pub temporal_client: TemporalClient,
-    pub websocket: WebSocketService,
+    pub progress: ProgressHub,
 }
 
 #[tokio::main]
@@ -51,13 +52,13 @@ async fn main() -> Result<()> {
     let api_client = ApiClient::new().await?;
     let redis = RedisService::new().await?;
     let temporal_client = TemporalClient::new().await?;
-    let websocket = WebSocketService::new();
+    let progress = ProgressHub::from_env()?;
 
     let state = AppState { 
         api_client, 
         redis, 
         temporal_client,
-        websocket,
+        progress,
     };
 
     // Build the application router
@@ -127,8 +128,8 @@ mod tests {
         let api_client = ApiClient::new().await.unwrap();
         let redis = RedisService::new().await.unwrap();
         let temporal_client = TemporalClient::new().await.unwrap();
-        let websocket = WebSocketService::new();
-        let state = AppState { api_client, redis, temporal_client, websocket };
+        let progress = ProgressHub::from_env().unwrap();
+        let state = AppState { api_client, redis, temporal_client, progress };
         
         let app = create_app(state);
         let server = TestServer::new(app).unwrap();
@@ -29,6 +29,7 @@ pub fn create_routes() -> Router<AppState> {
         .route("/metrics/workflow/:workflow_id", get(get_workflow_specific_metrics))
         .route("/performance", get(get_performance_metrics))
         .route("/alerts", get(get_workflow_alerts))
+        .route("/slas", get(get_workflow_sla_status))
         .route("/capacity", get(get_capacity_metrics))
         .route("/trends", get(get_workflow_trends))
 }
@@ -554,6 +555,49 @@ async fn get_workflow_alerts(
     }))
 }
 
+// Get workflow SLA status from the workflow service
+async fn get_workflow_sla_status(
+    State(state): State<AppState>,
+    request: Request,
+) -> BffResult<Json<ApiResponse<serde_json::Value>>> {
+    let claims = request.extensions().get::<Claims>()
+        .ok_or_else(|| BffError::authentication("Missing authentication claims"))?;
+    
+    let tenant_context = get_tenant_context(&request)
+        .ok_or_else(|| BffError::tenant_validation("Missing tenant context"))?;
+
+    // Check permissions
+    if !has_permission(claims, "monitoring:read") {
+        return Err(BffError::authorization("Insufficient permissions to view workflow SLAs"));
+    }
+
+    let token = request
+        .headers()
+        .get("authorization")
+        .and_then(|header| header.to_str().ok())
+        .and_then(|header| header.strip_prefix("Bearer "))
+        .ok_or_else(|| BffError::authentication("Missing bearer token"))?;
+
+    // SLA status is evaluated on every read, so it isn't cached
+    let status = state
+        .api_client
+        .get_workflow_sla_status(&tenant_context.tenant_id, token)
+        .await?;
+
+    info!("Fetched workflow SLA status for tenant: {}", tenant_context.tenant_id);
+
+    Ok(Json(ApiResponse {
+        data: status,
+        meta: Some(ResponseMeta {
+            total: None,
+            page: None,
+            per_page: None,
+            cached: Some(false),
+            cache_ttl: None,
+        }),
+    }))
+}
+
 // Get capacity metrics
 async fn get_capacity_metrics(
     State(state): State<AppState>,
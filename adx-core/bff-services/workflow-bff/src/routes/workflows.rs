@@ -1,7 +1,8 @@
+use adx_bff_shared::{progress_response, request_token, ProgressUpdate};
 use axum::{
-    extract::{Path, State, Extension, Query},
-    http::StatusCode,
-    response::Json,
+    extract::{ws::WebSocketUpgrade, Path, State, Extension, Query},
+    http::{HeaderMap, StatusCode, Uri},
+    response::{IntoResponse, Json, Response},
     routing::{get, post},
     Router,
 };
@@ -22,6 +23,7 @@ pub fn create_routes() -> Router<AppState> {
         .route("/", get(list_workflows))
         .route("/:workflow_id", get(get_workflow))
         .route("/:workflow_id/status", get(get_workflow_status))
+        .route("/:workflow_id/stream", get(stream_workflow_progress))
         .route("/:workflow_id/cancel", post(cancel_workflow))
         .route("/:workflow_id/retry", post(retry_workflow))
         .route("/user/:user_id", get(get_user_workflows))
@@ -105,15 +107,39 @@ async fn get_workflow_status(
     }
 }
 
+/// Progress pushed as server-sent events, or over a WebSocket when the
+/// client upgrades, until the workflow finishes
+async fn stream_workflow_progress(
+    State(state): State<AppState>,
+    Path(workflow_id): Path<String>,
+    Extension(_claims): Extension<Claims>,
+    Extension(tenant): Extension<TenantContext>,
+    headers: HeaderMap,
+    uri: Uri,
+    upgrade: Option<WebSocketUpgrade>,
+) -> Response {
+    let Some(token) = request_token(&headers, &uri) else {
+        return StatusCode::UNAUTHORIZED.into_response();
+    };
+
+    match state.progress.subscribe(&workflow_id, &tenant.tenant_id, &token).await {
+        Ok(subscription) => progress_response(upgrade, subscription),
+        Err(e) => e.into_response(),
+    }
+}
+
 async fn cancel_workflow(
-    State(_state): State<AppState>,
+    State(state): State<AppState>,
     Path(workflow_id): Path<String>,
     Extension(_claims): Extension<Claims>,
-    Extension(_tenant): Extension<TenantContext>,
+    Extension(tenant): Extension<TenantContext>,
 ) -> Result<Json<Value>, StatusCode> {
     // In a real implementation, this would cancel the Temporal workflow
     tracing::info!("Cancelling workflow: {}", workflow_id);
     
+    // Streams learn of it now rather than at their next status lookup
+    state.progress.publish(&tenant.tenant_id, ProgressUpdate::new(&workflow_id, "CANCELLED"));
+    
     Ok(Json(json!({
         "workflow_id": workflow_id,
         "status": "CANCELLED",
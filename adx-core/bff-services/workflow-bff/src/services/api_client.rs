@@ -7,18 +7,21 @@ use std::time::Duration;
 pub struct ApiClient {
     client: Client,
     base_url: String,
+    workflow_service_url: String,
 }
 
 impl ApiClient {
     pub async fn new() -> Result<Self> {
         let base_url = std::env::var("API_GATEWAY_URL")
             .unwrap_or_else(|_| "http://localhost:8080".to_string());
+        let workflow_service_url = std::env::var("WORKFLOW_SERVICE_URL")
+            .unwrap_or_else(|_| "http://localhost:8084".to_string());
 
         let client = Client::builder()
             .timeout(Duration::from_secs(30))
             .build()?;
 
-        Ok(Self { client, base_url })
+        Ok(Self { client, base_url, workflow_service_url })
     }
 
     pub async fn get_workflow(&self, workflow_id: &str, token: &str) -> Result<Value> {
@@ -54,4 +57,21 @@ impl ApiClient {
         let json = response.json::<Value>().await?;
         Ok(json)
     }
+
+    /// Evaluate the tenant's workflow SLAs in the workflow service
+    pub async fn get_workflow_sla_status(&self, tenant_id: &str, token: &str) -> Result<Value> {
+        let url = format!("{}/api/v1/workflow-slas/status", self.workflow_service_url);
+
+        let response = self
+            .client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", token))
+            .header("X-Tenant-ID", tenant_id)
+            .send()
+            .await?
+            .error_for_status()?;
+
+        let json = response.json::<Value>().await?;
+        Ok(json)
+    }
 }
\ No newline at end of file
@@ -1,4 +1,3 @@
 pub mod api_client;
 pub mod redis;
-pub mod temporal_client;
-pub mod websocket;
\ No newline at end of file
+pub mod temporal_client;
\ No newline at end of file
@@ -2,4 +2,5 @@
 export { SharedContextProvider } from './providers/SharedContextProvider';
 export { TenantProvider, useTenantContext } from './providers/TenantProvider';
 export { UserProvider, useUserContext } from './providers/UserProvider';
-export { AuthProvider, useAuthContext } from './providers/AuthProvider';
\ No newline at end of file
+export { AuthProvider, useAuthContext } from './providers/AuthProvider';
+export { useAuthStore } from './auth-store';
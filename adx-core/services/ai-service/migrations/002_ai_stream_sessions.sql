@@ -0,0 +1,20 @@
+-- Streamed generations, persisted as they arrive so that clients can resume them
+CREATE TABLE ai_stream_sessions (
+    id UUID PRIMARY KEY,
+    tenant_id VARCHAR(255) NOT NULL,
+    user_id VARCHAR(255) NOT NULL,
+    workflow_id VARCHAR(255),
+    model VARCHAR(255) NOT NULL,
+    output TEXT NOT NULL DEFAULT '',
+    status VARCHAR(50) NOT NULL, -- 'Streaming', 'Completed', 'Failed'
+    finish_reason VARCHAR(50),
+    usage JSONB, -- JSON serialized TokenUsage
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_ai_stream_sessions_tenant_id ON ai_stream_sessions(tenant_id);
+CREATE INDEX idx_ai_stream_sessions_workflow_id ON ai_stream_sessions(workflow_id) WHERE workflow_id IS NOT NULL;
+
+CREATE TRIGGER update_ai_stream_sessions_updated_at BEFORE UPDATE ON ai_stream_sessions FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,23 @@
+-- Tenant-scoped vector store for embeddings (requires the pgvector extension)
+CREATE EXTENSION IF NOT EXISTS vector;
+
+CREATE TABLE ai_embeddings (
+    tenant_id VARCHAR(255) NOT NULL,
+    collection VARCHAR(255) NOT NULL,
+    id VARCHAR(255) NOT NULL,
+    model VARCHAR(255) NOT NULL,
+    dimensions INTEGER NOT NULL,
+    -- Unsized so that collections can use models of different sizes;
+    -- queries compare only vectors of the same model and dimensions
+    embedding vector NOT NULL,
+    content TEXT NOT NULL,
+    metadata JSONB NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tenant_id, collection, id)
+);
+
+CREATE INDEX idx_ai_embeddings_collection_model ON ai_embeddings(tenant_id, collection, model);
+CREATE INDEX idx_ai_embeddings_metadata ON ai_embeddings USING GIN (metadata);
+
+CREATE TRIGGER update_ai_embeddings_updated_at BEFORE UPDATE ON ai_embeddings FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,67 @@
+-- Versioned prompt templates. Rows without a tenant are the built-in
+-- templates, which a tenant overrides by creating a template of the same name.
+CREATE TABLE ai_prompt_templates (
+    id UUID PRIMARY KEY,
+    tenant_id VARCHAR(255),
+    name VARCHAR(255) NOT NULL,
+    version INTEGER NOT NULL,
+    description TEXT,
+    template TEXT NOT NULL, -- Prompt text with {{variable}} placeholders
+    variables JSONB NOT NULL DEFAULT '[]', -- JSON serialized Vec<PromptVariable>
+    model VARCHAR(255),
+    parameters JSONB NOT NULL DEFAULT '{}', -- JSON serialized AIParameters
+    created_by VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE UNIQUE INDEX idx_ai_prompt_templates_version ON ai_prompt_templates(COALESCE(tenant_id, ''), name, version);
+CREATE INDEX idx_ai_prompt_templates_tenant_name ON ai_prompt_templates(tenant_id, name);
+
+-- Built-in templates used by the AI workflows
+INSERT INTO ai_prompt_templates (id, tenant_id, name, version, description, template, variables, parameters) VALUES
+(gen_random_uuid(), NULL, 'onboarding.welcome', 1, 'Welcome message for a new user',
+ 'Create a personalized welcome message for a new user named {{name}} with role {{role}} in department {{department}}.
+Their experience level is {{experience_level}} and they''re interested in: {{interests}}.
+Communication style should be {{communication_style}}. Keep it warm, professional, and encouraging.',
+ '[{"name": "name"}, {"name": "role"}, {"name": "department", "default": "unspecified"}, {"name": "experience_level"}, {"name": "interests"}, {"name": "communication_style"}]',
+ '{"max_tokens": 300, "temperature": 0.7}'),
+(gen_random_uuid(), NULL, 'onboarding.features', 1, 'Feature recommendations for a new user',
+ 'Based on a user''s profile - Role: {{role}}, Experience: {{experience_level}}, Interests: {{interests}}, Goals: {{goals}} -
+recommend the top 5 most relevant features from our platform.
+Return as a JSON array of feature names with brief explanations.',
+ '[{"name": "role"}, {"name": "experience_level"}, {"name": "interests"}, {"name": "goals"}]',
+ '{"max_tokens": 400, "temperature": 0.5}'),
+(gen_random_uuid(), NULL, 'onboarding.learning_path', 1, 'Learning path for a new user',
+ 'Create a personalized learning path for a {{role}} user with {{experience_level}} experience level.
+Their learning pace preference is {{learning_pace}} and they want to achieve: {{goals}}.
+Create 4-6 learning steps with titles, descriptions, estimated time, and difficulty level.',
+ '[{"name": "role"}, {"name": "experience_level"}, {"name": "learning_pace"}, {"name": "goals"}]',
+ '{"max_tokens": 600, "temperature": 0.6}'),
+(gen_random_uuid(), NULL, 'onboarding.setup_tasks', 1, 'Initial setup tasks for a new user',
+ 'Create a prioritized list of initial setup tasks for a new {{role}} user.
+Consider their experience level ({{experience_level}}) and preferred features: {{preferred_features}}.
+Include task titles, descriptions, priorities (High/Medium/Low), and estimated time.',
+ '[{"name": "role"}, {"name": "experience_level"}, {"name": "preferred_features"}]',
+ '{"max_tokens": 500, "temperature": 0.4}'),
+(gen_random_uuid(), NULL, 'documents.sentiment', 1, 'Sentiment analysis of a document',
+ 'Analyze the sentiment of the following {{document_type}} document.
+Provide the overall sentiment (Positive, Negative, Neutral) and confidence score.
+Also identify key emotions present (if any): joy, anger, fear, sadness, surprise, trust.
+
+Document: {{document}}',
+ '[{"name": "document_type"}, {"name": "document"}]',
+ '{"max_tokens": 200, "temperature": 0.3}'),
+(gen_random_uuid(), NULL, 'email.generate', 1, 'Email with subject line',
+ 'Generate a {{email_type}} email with the following specifications:
+
+{{details}}
+Tone: {{tone}}
+Length: {{length}}
+Personalization: {{personalization}}
+
+Generate both subject line and email body. Format as:
+SUBJECT: [subject line]
+BODY:
+[email body]',
+ '[{"name": "email_type"}, {"name": "details", "default": ""}, {"name": "tone"}, {"name": "length"}, {"name": "personalization"}]',
+ '{"max_tokens": 600, "temperature": 0.7}');
@@ -0,0 +1,15 @@
+-- Tenant moderation policies; tenants without a row use the service defaults
+CREATE TABLE ai_moderation_policies (
+    tenant_id VARCHAR(255) PRIMARY KEY,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    provider_check BOOLEAN NOT NULL DEFAULT TRUE,
+    provider_action VARCHAR(50) NOT NULL DEFAULT 'block', -- 'block', 'flag'
+    blocked_keywords JSONB NOT NULL DEFAULT '[]',
+    keyword_action VARCHAR(50) NOT NULL DEFAULT 'block', -- 'block', 'redact', 'flag'
+    pii_types JSONB NOT NULL DEFAULT '[]', -- e.g. ["email", "credit_card"]
+    pii_action VARCHAR(50) NOT NULL DEFAULT 'redact',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER update_ai_moderation_policies_updated_at BEFORE UPDATE ON ai_moderation_policies FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,28 @@
+-- Models served by Azure OpenAI, AWS Bedrock and Google Gemini
+INSERT INTO ai_models (id, name, provider, capabilities, max_tokens, cost_per_token, tier_availability) VALUES
+('azure-gpt-35-turbo', 'GPT-3.5 Turbo (Azure)', 'AzureOpenAI',
+ ARRAY['TextGeneration', 'TextClassification', 'TextSummarization', 'EntityExtraction', 'SentimentAnalysis'],
+ 4096, 0.0000015, ARRAY['Professional', 'Enterprise']),
+('azure-gpt-4', 'GPT-4 (Azure)', 'AzureOpenAI',
+ ARRAY['TextGeneration', 'TextClassification', 'TextSummarization', 'EntityExtraction', 'SentimentAnalysis', 'CodeGeneration'],
+ 8192, 0.00003, ARRAY['Enterprise']),
+('anthropic.claude-3-haiku-20240307-v1:0', 'Claude 3 Haiku (Bedrock)', 'Bedrock',
+ ARRAY['TextGeneration', 'TextClassification', 'TextSummarization', 'EntityExtraction'],
+ 4096, 0.00000025, ARRAY['Professional', 'Enterprise']),
+('anthropic.claude-3-sonnet-20240229-v1:0', 'Claude 3 Sonnet (Bedrock)', 'Bedrock',
+ ARRAY['TextGeneration', 'TextClassification', 'TextSummarization', 'EntityExtraction', 'SentimentAnalysis', 'CodeGeneration'],
+ 4096, 0.000003, ARRAY['Enterprise']),
+('meta.llama3-70b-instruct-v1:0', 'Llama 3 70B Instruct (Bedrock)', 'Bedrock',
+ ARRAY['TextGeneration', 'TextClassification', 'TextSummarization', 'CodeGeneration'],
+ 8192, 0.00000265, ARRAY['Enterprise']),
+('gemini-1.5-flash', 'Gemini 1.5 Flash', 'Gemini',
+ ARRAY['TextGeneration', 'TextClassification', 'TextSummarization', 'EntityExtraction', 'SentimentAnalysis'],
+ 1048576, 0.000000075, ARRAY['Professional', 'Enterprise']),
+('gemini-1.5-pro', 'Gemini 1.5 Pro', 'Gemini',
+ ARRAY['TextGeneration', 'TextClassification', 'TextSummarization', 'EntityExtraction', 'SentimentAnalysis', 'CodeGeneration'],
+ 2097152, 0.00000125, ARRAY['Enterprise']);
+
+INSERT INTO ai_provider_health (provider, status, last_check) VALUES
+('AzureOpenAI', 'Healthy', NOW()),
+('Bedrock', 'Healthy', NOW()),
+('Gemini', 'Healthy', NOW());
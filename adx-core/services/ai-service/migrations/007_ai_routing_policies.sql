@@ -0,0 +1,10 @@
+-- Tenant provider routing policies; tenants without a row may use every provider
+CREATE TABLE ai_routing_policies (
+    tenant_id VARCHAR(255) PRIMARY KEY,
+    self_hosted_only BOOLEAN NOT NULL DEFAULT FALSE, -- data-residency constraint
+    allowed_providers JSONB NOT NULL DEFAULT '[]', -- e.g. ["Local", "AzureOpenAI"]; empty allows all
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER update_ai_routing_policies_updated_at BEFORE UPDATE ON ai_routing_policies FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
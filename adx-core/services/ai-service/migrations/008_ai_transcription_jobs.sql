@@ -0,0 +1,21 @@
+-- Transcriptions of audio files stored in file-service, indexed into the
+-- tenant's vector store once transcribed
+CREATE TABLE ai_transcription_jobs (
+    id UUID PRIMARY KEY,
+    tenant_id VARCHAR(255) NOT NULL,
+    user_id VARCHAR(255) NOT NULL,
+    workflow_id VARCHAR(255),
+    file_id UUID NOT NULL,
+    collection VARCHAR(255) NOT NULL,
+    status VARCHAR(50) NOT NULL, -- 'Pending', 'Running', 'Completed', 'Failed'
+    transcript JSONB, -- JSON serialized TranscriptionResult
+    documents INTEGER NOT NULL DEFAULT 0,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_ai_transcription_jobs_tenant_id ON ai_transcription_jobs(tenant_id);
+CREATE INDEX idx_ai_transcription_jobs_file_id ON ai_transcription_jobs(tenant_id, file_id);
+
+CREATE TRIGGER update_ai_transcription_jobs_updated_at BEFORE UPDATE ON ai_transcription_jobs FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,39 @@
+-- Cached completions per tenant. Responses are found by the hash of the
+-- normalized prompt, model and parameters, or by the similarity of the
+-- prompt's embedding to those of cached prompts with the same model and
+-- parameters.
+CREATE TABLE ai_response_cache (
+    id UUID PRIMARY KEY,
+    tenant_id VARCHAR(255) NOT NULL,
+    cache_key VARCHAR(64) NOT NULL, -- SHA-256 of capability, model, parameters and prompt
+    parameters_key VARCHAR(64) NOT NULL, -- SHA-256 of capability, model and parameters
+    capability VARCHAR(50) NOT NULL,
+    model VARCHAR(255) NOT NULL,
+    prompt TEXT NOT NULL, -- Normalized prompt
+    embedding_model VARCHAR(255),
+    dimensions INTEGER,
+    embedding vector, -- Set when the prompt was embedded for similarity matching
+    response JSONB NOT NULL,
+    estimated_cost DOUBLE PRECISION NOT NULL DEFAULT 0, -- Provider cost of producing the response
+    hits INTEGER NOT NULL DEFAULT 0,
+    last_hit_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE UNIQUE INDEX idx_ai_response_cache_key ON ai_response_cache(tenant_id, cache_key);
+CREATE INDEX idx_ai_response_cache_parameters ON ai_response_cache(tenant_id, parameters_key, embedding_model);
+CREATE INDEX idx_ai_response_cache_expires_at ON ai_response_cache(tenant_id, expires_at);
+
+-- Tenant cache policies; tenants without a row use the service defaults
+CREATE TABLE ai_cache_policies (
+    tenant_id VARCHAR(255) PRIMARY KEY,
+    enabled BOOLEAN NOT NULL,
+    ttl_seconds INTEGER NOT NULL,
+    semantic_matching BOOLEAN NOT NULL DEFAULT FALSE,
+    similarity_threshold REAL NOT NULL DEFAULT 0.95,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER update_ai_cache_policies_updated_at BEFORE UPDATE ON ai_cache_policies FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,43 @@
+-- Classification and summarization of many file-service documents. Items
+-- are checkpointed as they complete, so an interrupted batch resumes with
+-- the items that are still pending.
+CREATE TABLE ai_batch_jobs (
+    id UUID PRIMARY KEY,
+    tenant_id VARCHAR(255) NOT NULL,
+    user_id VARCHAR(255) NOT NULL,
+    workflow_id VARCHAR(255),
+    operations JSONB NOT NULL, -- JSON serialized BatchOperations
+    model VARCHAR(255) NOT NULL,
+    status VARCHAR(50) NOT NULL, -- 'Pending', 'Running', 'Completed', 'Failed'
+    total_items INTEGER NOT NULL DEFAULT 0,
+    completed_items INTEGER NOT NULL DEFAULT 0,
+    failed_items INTEGER NOT NULL DEFAULT 0,
+    prompt_tokens BIGINT NOT NULL DEFAULT 0,
+    completion_tokens BIGINT NOT NULL DEFAULT 0,
+    estimated_cost DOUBLE PRECISION NOT NULL DEFAULT 0,
+    report_file_id UUID, -- CSV report stored in file-service
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_ai_batch_jobs_tenant_id ON ai_batch_jobs(tenant_id, created_at DESC);
+
+CREATE TABLE ai_batch_items (
+    job_id UUID NOT NULL REFERENCES ai_batch_jobs(id) ON DELETE CASCADE,
+    file_id UUID NOT NULL,
+    filename VARCHAR(1024) NOT NULL,
+    status VARCHAR(50) NOT NULL, -- 'Pending', 'Completed', 'Failed'
+    classification VARCHAR(255),
+    confidence REAL,
+    summary TEXT,
+    error TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (job_id, file_id)
+);
+
+CREATE INDEX idx_ai_batch_items_status ON ai_batch_items(job_id, status);
+
+CREATE TRIGGER update_ai_batch_jobs_updated_at BEFORE UPDATE ON ai_batch_jobs FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+CREATE TRIGGER update_ai_batch_items_updated_at BEFORE UPDATE ON ai_batch_items FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
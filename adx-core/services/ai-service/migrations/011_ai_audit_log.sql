@@ -0,0 +1,41 @@
+-- Every AI request and what the model answered, for compliance review.
+-- Prompts and responses are stored as the tenant's audit policy says, with
+-- PII redacted, and purged once they expire.
+CREATE TABLE ai_audit_log (
+    id UUID PRIMARY KEY,
+    tenant_id VARCHAR(255) NOT NULL,
+    user_id VARCHAR(255) NOT NULL,
+    workflow_id VARCHAR(255),
+    capability VARCHAR(100) NOT NULL,
+    model VARCHAR(255) NOT NULL,
+    prompt TEXT, -- NULL when the policy does not store prompts
+    response TEXT, -- NULL when the policy does not store responses, or there was none
+    redactions INTEGER NOT NULL DEFAULT 0,
+    outcome VARCHAR(50) NOT NULL, -- 'Success', 'Blocked', 'Failed'
+    error TEXT,
+    cached BOOLEAN NOT NULL DEFAULT FALSE,
+    prompt_tokens INTEGER NOT NULL DEFAULT 0,
+    completion_tokens INTEGER NOT NULL DEFAULT 0,
+    estimated_cost DOUBLE PRECISION NOT NULL DEFAULT 0,
+    exported_at TIMESTAMPTZ, -- sent to security-service
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_ai_audit_log_tenant_id ON ai_audit_log(tenant_id, created_at DESC);
+CREATE INDEX idx_ai_audit_log_unexported ON ai_audit_log(tenant_id, created_at) WHERE exported_at IS NULL;
+CREATE INDEX idx_ai_audit_log_expires_at ON ai_audit_log(expires_at);
+
+-- Tenant audit policies; tenants without a row use the service defaults
+CREATE TABLE ai_audit_policies (
+    tenant_id VARCHAR(255) PRIMARY KEY,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    store_prompts BOOLEAN NOT NULL DEFAULT TRUE,
+    store_responses BOOLEAN NOT NULL DEFAULT TRUE,
+    redacted_pii_types JSONB NOT NULL DEFAULT '[]', -- e.g. ["email", "credit_card"]
+    retention_days INTEGER NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER update_ai_audit_policies_updated_at BEFORE UPDATE ON ai_audit_policies FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
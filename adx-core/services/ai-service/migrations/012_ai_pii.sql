@@ -0,0 +1,27 @@
+-- Tenant PII policies: which PII is redacted or tokenized from texts before
+-- they leave the platform. Tenants without a row use the service defaults.
+CREATE TABLE ai_pii_policies (
+    tenant_id VARCHAR(255) PRIMARY KEY,
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    pii_types JSONB NOT NULL DEFAULT '[]', -- e.g. ["email", "ssn"]
+    entity_types JSONB NOT NULL DEFAULT '[]', -- e.g. ["Person", "Location"]
+    mode VARCHAR(50) NOT NULL, -- 'redact', 'tokenize'
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER update_ai_pii_policies_updated_at BEFORE UPDATE ON ai_pii_policies FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Values replaced by tokens, restored in generated text until they expire.
+-- Tokens are derived from the value, so a value keeps its token.
+CREATE TABLE ai_pii_tokens (
+    tenant_id VARCHAR(255) NOT NULL,
+    token VARCHAR(100) NOT NULL,
+    value TEXT NOT NULL,
+    category VARCHAR(100) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL,
+    PRIMARY KEY (tenant_id, token)
+);
+
+CREATE INDEX idx_ai_pii_tokens_expires_at ON ai_pii_tokens(expires_at);
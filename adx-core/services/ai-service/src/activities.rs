@@ -1,24 +1,35 @@
-use crate::error::{ActivityError, AIResult};
+use crate::error::{AIError, ActivityError, AIResult};
 use crate::models::AIModelRegistry;
+use crate::moderation::Moderator;
 use crate::providers::AIProviderManager;
-use crate::services::{AIService, UsageTracker};
+use crate::services::{
+    AIService, AudioService, AuditLog, BatchProcessor, BudgetEnforcer, PromptTemplateStore, ProviderRouter, StreamSessionStore,
+    UsageTracker,
+};
 use crate::types::*;
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use std::sync::Arc;
-use crate::temporal_stubs::ActContext;
+use crate::temporal::ActContext;
 
 #[async_trait]
 pub trait AIActivities {
     async fn generate_text(&self, ctx: ActContext, request: TextGenerationRequest) -> Result<TextGenerationResult, ActivityError>;
+    async fn stream_text_generation(&self, ctx: ActContext, request: StreamTextGenerationRequest) -> Result<StreamSession, ActivityError>;
     async fn classify_text(&self, ctx: ActContext, request: TextClassificationRequest) -> Result<TextClassificationResult, ActivityError>;
     async fn summarize_text(&self, ctx: ActContext, request: TextSummarizationRequest) -> Result<TextSummarizationResult, ActivityError>;
     async fn extract_entities(&self, ctx: ActContext, request: EntityExtractionRequest) -> Result<EntityExtractionResult, ActivityError>;
     async fn validate_ai_request(&self, ctx: ActContext, request: AIRequest) -> Result<ValidationResult, ActivityError>;
     async fn track_ai_usage(&self, ctx: ActContext, usage_record: AIUsageRecord) -> Result<(), ActivityError>;
     async fn check_ai_quotas(&self, ctx: ActContext, context: RequestContext, capability: AICapability) -> Result<QuotaCheckResult, ActivityError>;
+    async fn render_prompt(&self, ctx: ActContext, request: RenderPromptRequest) -> Result<RenderedPrompt, ActivityError>;
+    async fn transcribe_file(&self, ctx: ActContext, request: TranscribeFileRequest) -> Result<TranscriptionJob, ActivityError>;
+    async fn prepare_batch_job(&self, ctx: ActContext, request: PrepareBatchRequest) -> Result<BatchPlan, ActivityError>;
+    async fn process_batch_item(&self, ctx: ActContext, request: ProcessBatchItemRequest) -> Result<ProcessedBatchItem, ActivityError>;
+    async fn finish_batch_job(&self, ctx: ActContext, request: FinishBatchRequest) -> Result<BatchJob, ActivityError>;
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ValidationResult {
     pub is_valid: bool,
     pub errors: Vec<String>,
@@ -27,7 +38,7 @@ pub struct ValidationResult {
     pub estimated_tokens: u32,
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct QuotaCheckResult {
     pub allowed: bool,
     pub remaining_requests: u32,
@@ -41,6 +52,14 @@ pub struct AIActivitiesImpl {
     provider_manager: Arc<AIProviderManager>,
     model_registry: Arc<AIModelRegistry>,
     usage_tracker: Arc<UsageTracker>,
+    stream_sessions: Arc<StreamSessionStore>,
+    budget: Arc<BudgetEnforcer>,
+    prompt_templates: Arc<PromptTemplateStore>,
+    moderator: Arc<Moderator>,
+    router: Arc<ProviderRouter>,
+    audio: Arc<AudioService>,
+    batches: Arc<BatchProcessor>,
+    audit: Arc<AuditLog>,
 }
 
 impl AIActivitiesImpl {
@@ -49,12 +68,28 @@ impl AIActivitiesImpl {
         provider_manager: Arc<AIProviderManager>,
         model_registry: Arc<AIModelRegistry>,
         usage_tracker: Arc<UsageTracker>,
+        stream_sessions: Arc<StreamSessionStore>,
+        budget: Arc<BudgetEnforcer>,
+        prompt_templates: Arc<PromptTemplateStore>,
+        moderator: Arc<Moderator>,
+        router: Arc<ProviderRouter>,
+        audio: Arc<AudioService>,
+        batches: Arc<BatchProcessor>,
+        audit: Arc<AuditLog>,
     ) -> Self {
         Self {
             ai_service,
             provider_manager,
             model_registry,
             usage_tracker,
+            stream_sessions,
+            budget,
+            prompt_templates,
+            moderator,
+            router,
+            audio,
+            batches,
+            audit,
         }
     }
     
@@ -71,6 +106,26 @@ impl AIActivitiesImpl {
         Ok(model.id.clone())
     }
     
+    /// Downgrade or reject the model when the tenant is over its monthly
+    /// budget, then move it to a provider the tenant's routing policy allows
+    async fn resolve_model(&self, model: &str, capability: &AICapability, context: &RequestContext) -> Result<String, ActivityError> {
+        let model = self.budget
+            .resolve_model(&context.tenant_id, model, capability)
+            .await
+            .map_err(|e| match e {
+                AIError::QuotaExceeded(reason) => ActivityError::QuotaExceeded(reason),
+                e => ActivityError::ExternalServiceError(e.to_string()),
+            })?;
+        
+        self.router
+            .route(&context.tenant_id, &model, capability)
+            .await
+            .map_err(|e| match e {
+                AIError::ModelNotAvailable(reason) => ActivityError::ModelUnavailable(reason),
+                e => ActivityError::ExternalServiceError(e.to_string()),
+            })
+    }
+    
     async fn validate_content(&self, content: &str) -> Result<(), ActivityError> {
         // Basic content validation (could be enhanced with more sophisticated filtering)
         if content.trim().is_empty() {
@@ -81,25 +136,67 @@ impl AIActivitiesImpl {
             return Err(ActivityError::InvalidInput("Content too large".to_string()));
         }
         
-        // Check for potentially harmful content (simplified)
-        let harmful_patterns = ["hack", "exploit", "malware", "virus"];
-        for pattern in &harmful_patterns {
-            if content.to_lowercase().contains(pattern) {
-                return Err(ActivityError::ContentPolicyViolation(
-                    format!("Content contains potentially harmful pattern: {}", pattern)
-                ));
+        Ok(())
+    }
+    
+    /// Apply the tenant's moderation policy to text sent to or generated by a model
+    async fn moderate(&self, context: &RequestContext, stage: ModerationStage, text: &str) -> Result<String, ActivityError> {
+        self.moderator
+            .moderate(context, stage, text)
+            .await
+            .map(|moderated| moderated.text)
+            .map_err(|e| match e {
+                AIError::ContentFiltered(reason) => ActivityError::ContentPolicyViolation(reason),
+                e => ActivityError::ExternalServiceError(e.to_string()),
+            })
+    }
+    
+    async fn audit<T: serde::Serialize>(
+        &self,
+        context: &RequestContext,
+        capability: AICapability,
+        model: &str,
+        prompt: &str,
+        outcome: &Result<T, ActivityError>,
+    ) {
+        let mut entry = AuditEntry {
+            context: context.clone(),
+            capability,
+            model: model.to_string(),
+            prompt: prompt.to_string(),
+            response: None,
+            outcome: AuditOutcome::Success,
+            error: None,
+            usage: None,
+            cached: false,
+        };
+        match outcome {
+            Ok(result) => {
+                let response = serde_json::to_value(result).ok();
+                entry.usage = response
+                    .as_ref()
+                    .and_then(|response| response.get("usage"))
+                    .and_then(|usage| serde_json::from_value(usage.clone()).ok());
+                entry.response = response.map(|response| response.to_string());
+            }
+            Err(e) => {
+                entry.outcome = match e {
+                    ActivityError::ContentPolicyViolation(_) => AuditOutcome::Blocked,
+                    _ => AuditOutcome::Failed,
+                };
+                entry.error = Some(e.to_string());
             }
         }
-        
-        Ok(())
+        self.audit.record(entry).await;
     }
 }
 
 #[async_trait]
 impl AIActivities for AIActivitiesImpl {
-    async fn generate_text(&self, _ctx: ActContext, request: TextGenerationRequest) -> Result<TextGenerationResult, ActivityError> {
+    async fn generate_text(&self, _ctx: ActContext, mut request: TextGenerationRequest) -> Result<TextGenerationResult, ActivityError> {
         // Validate content
         self.validate_content(&request.prompt).await?;
+        request.prompt = self.moderate(&request.context, ModerationStage::Input, &request.prompt).await?;
         
         // Check quotas
         let quota_check = self.check_ai_quotas(
@@ -120,6 +217,8 @@ impl AIActivities for AIActivitiesImpl {
         } else {
             self.select_model_for_request(&AICapability::TextGeneration, &request.context)?
         };
+        let model = self.resolve_model(&model, &AICapability::TextGeneration, &request.context).await?;
+        request.model = Some(model.clone());
         
         // Get model info to determine provider
         let model_info = self.model_registry.get_model(&model)
@@ -130,20 +229,113 @@ impl AIActivities for AIActivitiesImpl {
             .map_err(|e| ActivityError::ExternalServiceError(e.to_string()))?;
         
         // Generate text
-        let result = provider.generate_text(&request).await
+        let outcome = async {
+            let mut result = _ctx.heartbeat_while(provider.generate_text(&request)).await
+                .map_err(|e| ActivityError::GenerationFailed(e.to_string()))?;
+            
+            // Track usage
+            let usage_record = AIUsageRecord {
+                id: uuid::Uuid::new_v4(),
+                tenant_id: request.context.tenant_id.clone(),
+                user_id: request.context.user_id.clone(),
+                workflow_id: request.context.workflow_id.clone(),
+                activity_id: request.context.activity_id.clone(),
+                model: model.clone(),
+                capability: AICapability::TextGeneration,
+                usage: result.usage.clone(),
+                request_timestamp: chrono::Utc::now(),
+                response_timestamp: chrono::Utc::now(),
+                success: true,
+                error_code: None,
+            };
+            
+            self.track_ai_usage(_ctx, usage_record).await?;
+            
+            // Moderate the output once its usage is recorded, as it is paid for either way
+            result.generated_text = self.moderate(&request.context, ModerationStage::Output, &result.generated_text).await?;
+            
+            Ok::<_, ActivityError>(result)
+        }.await;
+        self.audit(&request.context, AICapability::TextGeneration, &model, &request.prompt, &outcome).await;
+        
+        outcome
+    }
+    
+    async fn stream_text_generation(&self, _ctx: ActContext, request: StreamTextGenerationRequest) -> Result<StreamSession, ActivityError> {
+        let mut generation = request.generation;
+        
+        // Validate content
+        self.validate_content(&generation.prompt).await?;
+        generation.prompt = self.moderate(&generation.context, ModerationStage::Input, &generation.prompt).await?;
+        
+        // Check quotas
+        let quota_check = self.check_ai_quotas(
+            _ctx.clone(),
+            generation.context.clone(),
+            AICapability::TextGeneration,
+        ).await?;
+        
+        if !quota_check.allowed {
+            return Err(ActivityError::QuotaExceeded(
+                quota_check.reason.unwrap_or_else(|| "Quota exceeded".to_string())
+            ));
+        }
+        
+        // Select appropriate model if not specified
+        let model = if let Some(ref model) = generation.model {
+            model.clone()
+        } else {
+            self.select_model_for_request(&AICapability::TextGeneration, &generation.context)?
+        };
+        let model = self.resolve_model(&model, &AICapability::TextGeneration, &generation.context).await?;
+        generation.model = Some(model.clone());
+        
+        let session = self.stream_sessions.create(request.stream_id, &generation.context, &model).await
+            .map_err(|e| ActivityError::ExternalServiceError(e.to_string()))?;
+        
+        // A retry after an earlier attempt finished, or after clients have
+        // already seen part of the output, must not generate it again
+        if session.status == StreamStatus::Completed {
+            return Ok(session);
+        }
+        if !session.output.is_empty() {
+            return Err(ActivityError::GenerationFailed(
+                format!("Stream {} was interrupted after partial output", session.id)
+            ));
+        }
+        
+        // Get model info to determine provider
+        let model_info = self.model_registry.get_model(&model)
+            .ok_or_else(|| ActivityError::ModelUnavailable(format!("Model {} not found", model)))?;
+        
+        // Get provider
+        let provider = self.provider_manager.get_provider(&model_info.provider)
+            .map_err(|e| ActivityError::ExternalServiceError(e.to_string()))?;
+        
+        let request_timestamp = chrono::Utc::now();
+        let chunks = provider.generate_text_stream(&generation).await
+            .map_err(|e| ActivityError::GenerationFailed(e.to_string()))?;
+        
+        // Relay and persist the output
+        let session = _ctx.heartbeat_while(self.stream_sessions.record(&session, chunks)).await
             .map_err(|e| ActivityError::GenerationFailed(e.to_string()))?;
         
         // Track usage
         let usage_record = AIUsageRecord {
             id: uuid::Uuid::new_v4(),
-            tenant_id: request.context.tenant_id.clone(),
-            user_id: request.context.user_id.clone(),
-            workflow_id: request.context.workflow_id.clone(),
-            activity_id: request.context.activity_id.clone(),
+            tenant_id: generation.context.tenant_id.clone(),
+            user_id: generation.context.user_id.clone(),
+            workflow_id: generation.context.workflow_id.clone(),
+            activity_id: generation.context.activity_id.clone(),
             model: model.clone(),
             capability: AICapability::TextGeneration,
-            usage: result.usage.clone(),
-            request_timestamp: chrono::Utc::now(),
+            usage: session.usage.clone().unwrap_or(TokenUsage {
+                prompt_tokens: 0,
+                completion_tokens: 0,
+                total_tokens: 0,
+                estimated_cost: 0.0,
+            }),
+            request_timestamp,
             response_timestamp: chrono::Utc::now(),
             success: true,
             error_code: None,
@@ -151,12 +343,13 @@ impl AIActivities for AIActivitiesImpl {
         
         self.track_ai_usage(_ctx, usage_record).await?;
         
-        Ok(result)
+        Ok(session)
     }
     
-    async fn classify_text(&self, _ctx: ActContext, request: TextClassificationRequest) -> Result<TextClassificationResult, ActivityError> {
+    async fn classify_text(&self, _ctx: ActContext, mut request: TextClassificationRequest) -> Result<TextClassificationResult, ActivityError> {
         // Validate content
         self.validate_content(&request.text).await?;
+        request.text = self.moderate(&request.context, ModerationStage::Input, &request.text).await?;
         
         // Check quotas
         let quota_check = self.check_ai_quotas(
@@ -177,6 +370,8 @@ impl AIActivities for AIActivitiesImpl {
         } else {
             self.select_model_for_request(&AICapability::TextClassification, &request.context)?
         };
+        let model = self.resolve_model(&model, &AICapability::TextClassification, &request.context).await?;
+        request.model = Some(model.clone());
         
         // Get model info to determine provider
         let model_info = self.model_registry.get_model(&model)
@@ -187,33 +382,39 @@ impl AIActivities for AIActivitiesImpl {
             .map_err(|e| ActivityError::ExternalServiceError(e.to_string()))?;
         
         // Classify text
-        let result = provider.classify_text(&request).await
-            .map_err(|e| ActivityError::GenerationFailed(e.to_string()))?;
-        
-        // Track usage
-        let usage_record = AIUsageRecord {
-            id: uuid::Uuid::new_v4(),
-            tenant_id: request.context.tenant_id.clone(),
-            user_id: request.context.user_id.clone(),
-            workflow_id: request.context.workflow_id.clone(),
-            activity_id: request.context.activity_id.clone(),
-            model: model.clone(),
-            capability: AICapability::TextClassification,
-            usage: result.usage.clone(),
-            request_timestamp: chrono::Utc::now(),
-            response_timestamp: chrono::Utc::now(),
-            success: true,
-            error_code: None,
-        };
-        
-        self.track_ai_usage(_ctx, usage_record).await?;
+        let outcome = async {
+            let result = provider.classify_text(&request).await
+                .map_err(|e| ActivityError::GenerationFailed(e.to_string()))?;
+            
+            // Track usage
+            let usage_record = AIUsageRecord {
+                id: uuid::Uuid::new_v4(),
+                tenant_id: request.context.tenant_id.clone(),
+                user_id: request.context.user_id.clone(),
+                workflow_id: request.context.workflow_id.clone(),
+                activity_id: request.context.activity_id.clone(),
+                model: model.clone(),
+                capability: AICapability::TextClassification,
+                usage: result.usage.clone(),
+                request_timestamp: chrono::Utc::now(),
+                response_timestamp: chrono::Utc::now(),
+                success: true,
+                error_code: None,
+            };
+            
+            self.track_ai_usage(_ctx, usage_record).await?;
+            
+            Ok::<_, ActivityError>(result)
+        }.await;
+        self.audit(&request.context, AICapability::TextClassification, &model, &request.text, &outcome).await;
         
-        Ok(result)
+        outcome
     }
     
-    async fn summarize_text(&self, _ctx: ActContext, request: TextSummarizationRequest) -> Result<TextSummarizationResult, ActivityError> {
+    async fn summarize_text(&self, _ctx: ActContext, mut request: TextSummarizationRequest) -> Result<TextSummarizationResult, ActivityError> {
         // Validate content
         self.validate_content(&request.text).await?;
+        request.text = self.moderate(&request.context, ModerationStage::Input, &request.text).await?;
         
         // Check quotas
         let quota_check = self.check_ai_quotas(
@@ -234,6 +435,8 @@ impl AIActivities for AIActivitiesImpl {
         } else {
             self.select_model_for_request(&AICapability::TextSummarization, &request.context)?
         };
+        let model = self.resolve_model(&model, &AICapability::TextSummarization, &request.context).await?;
+        request.model = Some(model.clone());
         
         // Get model info to determine provider
         let model_info = self.model_registry.get_model(&model)
@@ -244,33 +447,40 @@ impl AIActivities for AIActivitiesImpl {
             .map_err(|e| ActivityError::ExternalServiceError(e.to_string()))?;
         
         // Summarize text
-        let result = provider.summarize_text(&request).await
-            .map_err(|e| ActivityError::GenerationFailed(e.to_string()))?;
-        
-        // Track usage
-        let usage_record = AIUsageRecord {
-            id: uuid::Uuid::new_v4(),
-            tenant_id: request.context.tenant_id.clone(),
-            user_id: request.context.user_id.clone(),
-            workflow_id: request.context.workflow_id.clone(),
-            activity_id: request.context.activity_id.clone(),
-            model: model.clone(),
-            capability: AICapability::TextSummarization,
-            usage: result.usage.clone(),
-            request_timestamp: chrono::Utc::now(),
-            response_timestamp: chrono::Utc::now(),
-            success: true,
-            error_code: None,
-        };
-        
-        self.track_ai_usage(_ctx, usage_record).await?;
+        let outcome = async {
+            let mut result = _ctx.heartbeat_while(provider.summarize_text(&request)).await
+                .map_err(|e| ActivityError::GenerationFailed(e.to_string()))?;
+            result.summary = self.moderate(&request.context, ModerationStage::Output, &result.summary).await?;
+            
+            // Track usage
+            let usage_record = AIUsageRecord {
+                id: uuid::Uuid::new_v4(),
+                tenant_id: request.context.tenant_id.clone(),
+                user_id: request.context.user_id.clone(),
+                workflow_id: request.context.workflow_id.clone(),
+                activity_id: request.context.activity_id.clone(),
+                model: model.clone(),
+                capability: AICapability::TextSummarization,
+                usage: result.usage.clone(),
+                request_timestamp: chrono::Utc::now(),
+                response_timestamp: chrono::Utc::now(),
+                success: true,
+                error_code: None,
+            };
+            
+            self.track_ai_usage(_ctx, usage_record).await?;
+            
+            Ok::<_, ActivityError>(result)
+        }.await;
+        self.audit(&request.context, AICapability::TextSummarization, &model, &request.text, &outcome).await;
         
-        Ok(result)
+        outcome
     }
     
-    async fn extract_entities(&self, _ctx: ActContext, request: EntityExtractionRequest) -> Result<EntityExtractionResult, ActivityError> {
+    async fn extract_entities(&self, _ctx: ActContext, mut request: EntityExtractionRequest) -> Result<EntityExtractionResult, ActivityError> {
         // Validate content
         self.validate_content(&request.text).await?;
+        request.text = self.moderate(&request.context, ModerationStage::Input, &request.text).await?;
         
         // Check quotas
         let quota_check = self.check_ai_quotas(
@@ -291,6 +501,8 @@ impl AIActivities for AIActivitiesImpl {
         } else {
             self.select_model_for_request(&AICapability::EntityExtraction, &request.context)?
         };
+        let model = self.resolve_model(&model, &AICapability::EntityExtraction, &request.context).await?;
+        request.model = Some(model.clone());
         
         // Get model info to determine provider
         let model_info = self.model_registry.get_model(&model)
@@ -301,28 +513,33 @@ impl AIActivities for AIActivitiesImpl {
             .map_err(|e| ActivityError::ExternalServiceError(e.to_string()))?;
         
         // Extract entities
-        let result = provider.extract_entities(&request).await
-            .map_err(|e| ActivityError::GenerationFailed(e.to_string()))?;
-        
-        // Track usage
-        let usage_record = AIUsageRecord {
-            id: uuid::Uuid::new_v4(),
-            tenant_id: request.context.tenant_id.clone(),
-            user_id: request.context.user_id.clone(),
-            workflow_id: request.context.workflow_id.clone(),
-            activity_id: request.context.activity_id.clone(),
-            model: model.clone(),
-            capability: AICapability::EntityExtraction,
-            usage: result.usage.clone(),
-            request_timestamp: chrono::Utc::now(),
-            response_timestamp: chrono::Utc::now(),
-            success: true,
-            error_code: None,
-        };
-        
-        self.track_ai_usage(_ctx, usage_record).await?;
+        let outcome = async {
+            let result = provider.extract_entities(&request).await
+                .map_err(|e| ActivityError::GenerationFailed(e.to_string()))?;
+            
+            // Track usage
+            let usage_record = AIUsageRecord {
+                id: uuid::Uuid::new_v4(),
+                tenant_id: request.context.tenant_id.clone(),
+                user_id: request.context.user_id.clone(),
+                workflow_id: request.context.workflow_id.clone(),
+                activity_id: request.context.activity_id.clone(),
+                model: model.clone(),
+                capability: AICapability::EntityExtraction,
+                usage: result.usage.clone(),
+                request_timestamp: chrono::Utc::now(),
+                response_timestamp: chrono::Utc::now(),
+                success: true,
+                error_code: None,
+            };
+            
+            self.track_ai_usage(_ctx, usage_record).await?;
+            
+            Ok::<_, ActivityError>(result)
+        }.await;
+        self.audit(&request.context, AICapability::EntityExtraction, &model, &request.text, &outcome).await;
         
-        Ok(result)
+        outcome
     }
     
     async fn validate_ai_request(&self, _ctx: ActContext, request: AIRequest) -> Result<ValidationResult, ActivityError> {
@@ -411,6 +628,140 @@ impl AIActivities for AIActivitiesImpl {
             reason,
         })
     }
+    
+    async fn render_prompt(&self, _ctx: ActContext, request: RenderPromptRequest) -> Result<RenderedPrompt, ActivityError> {
+        self.prompt_templates.render(&request.tenant_id, &request.template, &request.variables).await
+            .map_err(|e| match e {
+                AIError::NotFound(msg) | AIError::Validation(msg) => ActivityError::InvalidInput(msg),
+                e => ActivityError::ExternalServiceError(format!("Failed to render prompt: {}", e)),
+            })
+    }
+    
+    async fn transcribe_file(&self, _ctx: ActContext, mut request: TranscribeFileRequest) -> Result<TranscriptionJob, ActivityError> {
+        // Check quotas
+        let quota_check = self.check_ai_quotas(
+            _ctx.clone(),
+            request.context.clone(),
+            AICapability::SpeechToText,
+        ).await?;
+        
+        if !quota_check.allowed {
+            return Err(ActivityError::QuotaExceeded(
+                quota_check.reason.unwrap_or_else(|| "Quota exceeded".to_string())
+            ));
+        }
+        
+        let tenant_id = request.context.tenant_id.clone();
+        self.budget.ensure_within_budget(&tenant_id).await
+            .map_err(|e| match e {
+                AIError::QuotaExceeded(reason) => ActivityError::QuotaExceeded(reason),
+                e => ActivityError::ExternalServiceError(e.to_string()),
+            })?;
+        request.provider = self.router.route_provider(&tenant_id, request.provider.take(), "audio").await
+            .map_err(|e| ActivityError::ModelUnavailable(e.to_string()))?;
+        
+        let request_timestamp = chrono::Utc::now();
+        self.audio.create_job(&request).await.map_err(job_error)?;
+        let transcribed = _ctx.heartbeat_while(self.audio.run_job(&request)).await.map_err(job_error)?;
+        
+        // Track usage of the transcription and of the transcript's embeddings
+        let usages = [
+            transcribed.transcription.as_ref()
+                .map(|transcription| (&transcription.model, AICapability::SpeechToText, &transcription.usage)),
+            transcribed.index.as_ref()
+                .map(|index| (&index.model, AICapability::TextEmbedding, &index.usage)),
+        ];
+        for (model, capability, usage) in usages.into_iter().flatten() {
+            let usage_record = AIUsageRecord {
+                id: uuid::Uuid::new_v4(),
+                tenant_id: request.context.tenant_id.clone(),
+                user_id: request.context.user_id.clone(),
+                workflow_id: request.context.workflow_id.clone(),
+                activity_id: request.context.activity_id.clone(),
+                model: model.clone(),
+                capability,
+                usage: usage.clone(),
+                request_timestamp,
+                response_timestamp: chrono::Utc::now(),
+                success: true,
+                error_code: None,
+            };
+            
+            self.track_ai_usage(_ctx.clone(), usage_record).await?;
+        }
+        
+        Ok(transcribed.job)
+    }
+    
+    async fn prepare_batch_job(&self, _ctx: ActContext, request: PrepareBatchRequest) -> Result<BatchPlan, ActivityError> {
+        let tenant_id = request.context.tenant_id.clone();
+        self.budget.ensure_within_budget(&tenant_id).await
+            .map_err(|e| match e {
+                AIError::QuotaExceeded(reason) => ActivityError::QuotaExceeded(reason),
+                e => ActivityError::ExternalServiceError(e.to_string()),
+            })?;
+        
+        // Batches are served by one model, chosen for their first operation
+        let capability = if request.operations.classify.is_some() {
+            AICapability::TextClassification
+        } else {
+            AICapability::TextSummarization
+        };
+        let model = match &request.model {
+            Some(model) => model.clone(),
+            None => self.select_model_for_request(&capability, &request.context)?,
+        };
+        let model = self.resolve_model(&model, &capability, &request.context).await?;
+        
+        self.batches.prepare(&request, &model).await.map_err(job_error)
+    }
+    
+    async fn process_batch_item(&self, _ctx: ActContext, request: ProcessBatchItemRequest) -> Result<ProcessedBatchItem, ActivityError> {
+        let request_timestamp = chrono::Utc::now();
+        let processed = _ctx.heartbeat_while(self.batches.process_item(&request)).await.map_err(job_error)?;
+        
+        // Track usage of the document's operations
+        for (capability, usage) in &processed.usage {
+            let usage_record = AIUsageRecord {
+                id: uuid::Uuid::new_v4(),
+                tenant_id: request.context.tenant_id.clone(),
+                user_id: request.context.user_id.clone(),
+                workflow_id: request.context.workflow_id.clone(),
+                activity_id: request.context.activity_id.clone(),
+                model: processed.model.clone(),
+                capability: capability.clone(),
+                usage: usage.clone(),
+                request_timestamp,
+                response_timestamp: chrono::Utc::now(),
+                success: true,
+                error_code: None,
+            };
+            
+            self.track_ai_usage(_ctx.clone(), usage_record).await?;
+        }
+        
+        Ok(processed)
+    }
+    
+    async fn finish_batch_job(&self, _ctx: ActContext, request: FinishBatchRequest) -> Result<BatchJob, ActivityError> {
+        let context = &request.context;
+        match &request.error {
+            Some(error) => {
+                self.batches.fail(request.job_id, error).await.map_err(job_error)?;
+                self.batches.get_job(&context.tenant_id, request.job_id).await.map_err(job_error)
+            }
+            None => self.batches.complete(context, request.job_id).await.map_err(job_error),
+        }
+    }
+}
+
+fn job_error(e: AIError) -> ActivityError {
+    match e {
+        AIError::NotFound(msg) | AIError::Validation(msg) => ActivityError::InvalidInput(msg),
+        AIError::ContentFiltered(reason) => ActivityError::ContentPolicyViolation(reason),
+        AIError::ModelNotAvailable(reason) => ActivityError::ModelUnavailable(reason),
+        e => ActivityError::GenerationFailed(e.to_string()),
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -0,0 +1,62 @@
+use crate::audio::{request_speech, request_transcription, SpeechProvider};
+use crate::config::LocalAIConfig;
+use crate::error::AIResult;
+use crate::types::*;
+use async_trait::async_trait;
+use reqwest::Client;
+
+/// Transcription and speech from a self-hosted server exposing the
+/// OpenAI-compatible `/v1/audio` endpoints. Ollama does not serve audio,
+/// so this is a server of its own.
+pub struct LocalSpeechProvider {
+    client: Client,
+    config: LocalAIConfig,
+    base_url: String,
+}
+
+impl LocalSpeechProvider {
+    pub fn new(config: &LocalAIConfig, base_url: &str) -> Self {
+        Self {
+            client: Client::new(),
+            config: config.clone(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl SpeechProvider for LocalSpeechProvider {
+    async fn transcribe(&self, request: &TranscriptionRequest) -> AIResult<TranscriptionResult> {
+        let model = request.model.as_deref().unwrap_or(&self.config.transcription_model);
+
+        request_transcription(
+            &self.client,
+            &format!("{}/v1/audio/transcriptions", self.base_url),
+            None,
+            model,
+            0.0, // Local models have no API cost
+            request,
+        )
+        .await
+    }
+
+    async fn synthesize(&self, request: &SpeechSynthesisRequest) -> AIResult<SynthesizedSpeech> {
+        let model = request.model.as_deref().unwrap_or(&self.config.speech_model);
+        let voice = request.voice.as_deref().unwrap_or(&self.config.speech_voice);
+
+        request_speech(
+            &self.client,
+            &format!("{}/v1/audio/speech", self.base_url),
+            None,
+            model,
+            voice,
+            0.0,
+            request,
+        )
+        .await
+    }
+
+    fn get_provider_type(&self) -> crate::types::AIProvider {
+        crate::types::AIProvider::Local
+    }
+}
@@ -0,0 +1,202 @@
+pub mod local;
+pub mod openai;
+
+use crate::error::{AIError, AIResult};
+use crate::types::*;
+use async_trait::async_trait;
+use reqwest::{multipart, Client};
+use serde::{Deserialize, Serialize};
+
+#[async_trait]
+pub trait SpeechProvider: Send + Sync {
+    async fn transcribe(&self, request: &TranscriptionRequest) -> AIResult<TranscriptionResult>;
+    async fn synthesize(&self, request: &SpeechSynthesisRequest) -> AIResult<SynthesizedSpeech>;
+    fn get_provider_type(&self) -> crate::types::AIProvider;
+}
+
+pub struct SpeechProviderManager {
+    openai: Option<openai::OpenAISpeechProvider>,
+    local: Option<local::LocalSpeechProvider>,
+}
+
+impl SpeechProviderManager {
+    pub fn new(config: &crate::config::AIProvidersConfig) -> Self {
+        let openai = if !config.openai.api_key.is_empty() {
+            Some(openai::OpenAISpeechProvider::new(&config.openai))
+        } else {
+            None
+        };
+
+        let local = match &config.local.speech_base_url {
+            Some(base_url) if config.local.enabled => Some(local::LocalSpeechProvider::new(&config.local, base_url)),
+            _ => None,
+        };
+
+        Self { openai, local }
+    }
+
+    pub fn get_provider(&self, provider_type: &crate::types::AIProvider) -> AIResult<&dyn SpeechProvider> {
+        match provider_type {
+            crate::types::AIProvider::OpenAI => {
+                self.openai.as_ref()
+                    .map(|p| p as &dyn SpeechProvider)
+                    .ok_or_else(|| AIError::AIProvider("OpenAI audio not configured".to_string()))
+            }
+            crate::types::AIProvider::Local => {
+                self.local.as_ref()
+                    .map(|p| p as &dyn SpeechProvider)
+                    .ok_or_else(|| AIError::AIProvider("Local audio not configured".to_string()))
+            }
+            provider => {
+                Err(AIError::ModelNotAvailable(format!("{:?} audio is not supported", provider)))
+            }
+        }
+    }
+
+    /// The provider used when a request does not name one
+    pub fn default_provider(&self) -> AIResult<&dyn SpeechProvider> {
+        self.openai.as_ref()
+            .map(|p| p as &dyn SpeechProvider)
+            .or_else(|| self.local.as_ref().map(|p| p as &dyn SpeechProvider))
+            .ok_or_else(|| AIError::AIProvider("No audio provider configured".to_string()))
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct VerboseTranscription {
+    text: String,
+    language: Option<String>,
+    #[serde(default)]
+    duration: f64,
+    #[serde(default)]
+    segments: Vec<VerboseSegment>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VerboseSegment {
+    start: f64,
+    end: f64,
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+struct SpeechRequest<'a> {
+    model: &'a str,
+    input: &'a str,
+    voice: &'a str,
+    response_format: AudioFormat,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    speed: Option<f32>,
+}
+
+/// A transcript from an OpenAI-compatible `/v1/audio/transcriptions`
+/// endpoint. Transcription is billed by the minute of audio.
+pub(crate) async fn request_transcription(
+    client: &Client,
+    url: &str,
+    api_key: Option<&str>,
+    model: &str,
+    cost_per_minute: f64,
+    request: &TranscriptionRequest,
+) -> AIResult<TranscriptionResult> {
+    let audio = multipart::Part::bytes(request.audio.clone()).file_name(request.filename.clone());
+    let mut form = multipart::Form::new()
+        .part("file", audio)
+        .text("model", model.to_string())
+        .text("response_format", "verbose_json");
+    if let Some(language) = &request.language {
+        form = form.text("language", language.clone());
+    }
+    if let Some(prompt) = &request.prompt {
+        form = form.text("prompt", prompt.clone());
+    }
+
+    let mut builder = client.post(url).multipart(form);
+    if let Some(api_key) = api_key {
+        builder = builder.bearer_auth(api_key);
+    }
+
+    let response = builder.send().await.map_err(|e| AIError::HttpClient(e))?;
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(AIError::AIProvider(format!("Transcription API error: {}", error_text)));
+    }
+
+    let transcription = response
+        .json::<VerboseTranscription>()
+        .await
+        .map_err(|e| AIError::AIProvider(format!("Failed to parse transcription response: {}", e)))?;
+
+    let segments = transcription
+        .segments
+        .into_iter()
+        .map(|segment| TranscriptSegment {
+            start: segment.start,
+            end: segment.end,
+            text: segment.text.trim().to_string(),
+        })
+        .collect();
+
+    let text = transcription.text.trim().to_string();
+    // Tokens of the transcript are estimated for usage reporting
+    let completion_tokens = (text.len() / 4) as u32;
+
+    Ok(TranscriptionResult {
+        usage: TokenUsage {
+            prompt_tokens: 0,
+            completion_tokens,
+            total_tokens: completion_tokens,
+            estimated_cost: transcription.duration / 60.0 * cost_per_minute,
+        },
+        text,
+        language: transcription.language,
+        duration_seconds: transcription.duration,
+        segments,
+        model: model.to_string(),
+    })
+}
+
+/// Audio from an OpenAI-compatible `/v1/audio/speech` endpoint. Speech is
+/// billed by the character of input.
+pub(crate) async fn request_speech(
+    client: &Client,
+    url: &str,
+    api_key: Option<&str>,
+    model: &str,
+    voice: &str,
+    cost_per_character: f64,
+    request: &SpeechSynthesisRequest,
+) -> AIResult<SynthesizedSpeech> {
+    let mut builder = client.post(url).json(&SpeechRequest {
+        model,
+        input: &request.text,
+        voice,
+        response_format: request.format,
+        speed: request.speed,
+    });
+    if let Some(api_key) = api_key {
+        builder = builder.bearer_auth(api_key);
+    }
+
+    let response = builder.send().await.map_err(|e| AIError::HttpClient(e))?;
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(AIError::AIProvider(format!("Speech API error: {}", error_text)));
+    }
+
+    let audio = response.bytes().await?.to_vec();
+    // Tokens of the input are estimated for usage reporting
+    let prompt_tokens = (request.text.len() / 4) as u32;
+
+    Ok(SynthesizedSpeech {
+        audio,
+        format: request.format,
+        model: model.to_string(),
+        usage: TokenUsage {
+            prompt_tokens,
+            completion_tokens: 0,
+            total_tokens: prompt_tokens,
+            estimated_cost: request.text.chars().count() as f64 * cost_per_character,
+        },
+    })
+}
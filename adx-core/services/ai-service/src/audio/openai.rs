@@ -0,0 +1,71 @@
+use crate::audio::{request_speech, request_transcription, SpeechProvider};
+use crate::config::OpenAIConfig;
+use crate::error::AIResult;
+use crate::types::*;
+use async_trait::async_trait;
+use reqwest::Client;
+
+/// Whisper transcription and text-to-speech from the OpenAI audio API
+pub struct OpenAISpeechProvider {
+    client: Client,
+    config: OpenAIConfig,
+}
+
+impl OpenAISpeechProvider {
+    pub fn new(config: &OpenAIConfig) -> Self {
+        Self {
+            client: Client::new(),
+            config: config.clone(),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        let base_url = self.config.base_url.as_deref().unwrap_or("https://api.openai.com/v1");
+        format!("{}{}", base_url.trim_end_matches('/'), path)
+    }
+}
+
+/// Price per character of input of the OpenAI speech models
+fn cost_per_character(model: &str) -> f64 {
+    match model {
+        "tts-1-hd" => 0.00003,
+        _ => 0.000015, // tts-1
+    }
+}
+
+#[async_trait]
+impl SpeechProvider for OpenAISpeechProvider {
+    async fn transcribe(&self, request: &TranscriptionRequest) -> AIResult<TranscriptionResult> {
+        let model = request.model.as_deref().unwrap_or(&self.config.transcription_model);
+
+        request_transcription(
+            &self.client,
+            &self.url("/audio/transcriptions"),
+            Some(&self.config.api_key),
+            model,
+            0.006, // $0.006 per minute
+            request,
+        )
+        .await
+    }
+
+    async fn synthesize(&self, request: &SpeechSynthesisRequest) -> AIResult<SynthesizedSpeech> {
+        let model = request.model.as_deref().unwrap_or(&self.config.speech_model);
+        let voice = request.voice.as_deref().unwrap_or(&self.config.speech_voice);
+
+        request_speech(
+            &self.client,
+            &self.url("/audio/speech"),
+            Some(&self.config.api_key),
+            model,
+            voice,
+            cost_per_character(model),
+            request,
+        )
+        .await
+    }
+
+    fn get_provider_type(&self) -> crate::types::AIProvider {
+        crate::types::AIProvider::OpenAI
+    }
+}
@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::env;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -9,6 +10,14 @@ pub struct Config {
     pub ai_providers: AIProvidersConfig,
     pub monitoring: MonitoringConfig,
     pub security: SecurityConfig,
+    pub budget: BudgetConfig,
+    pub moderation: ModerationConfig,
+    pub audio: AudioConfig,
+    pub images: ImageConfig,
+    pub cache: CacheConfig,
+    pub batch: BatchConfig,
+    pub audit: AuditConfig,
+    pub pii: PiiConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -16,6 +25,9 @@ pub struct AIProvidersConfig {
     pub openai: OpenAIConfig,
     pub anthropic: AnthropicConfig,
     pub local: LocalAIConfig,
+    pub azure_openai: AzureOpenAIConfig,
+    pub bedrock: BedrockConfig,
+    pub gemini: GeminiConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -25,6 +37,13 @@ pub struct OpenAIConfig {
     pub default_model: String,
     pub max_tokens: u32,
     pub temperature: f32,
+    pub embedding_model: String,
+    pub transcription_model: String,
+    pub speech_model: String,
+    pub speech_voice: String,
+    pub image_model: String,
+    /// Chat model that answers questions about images
+    pub vision_model: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -35,11 +54,80 @@ pub struct AnthropicConfig {
     pub max_tokens: u32,
 }
 
+/// An Azure OpenAI resource. Azure serves models from named deployments;
+/// `deployments` maps the model ids requests use to those names.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AzureOpenAIConfig {
+    pub api_key: String,
+    /// e.g. `https://my-resource.openai.azure.com`
+    pub endpoint: String,
+    /// At least 2024-06-01, for usage reporting while streaming
+    pub api_version: String,
+    pub default_model: String,
+    pub deployments: HashMap<String, String>,
+    pub max_tokens: u32,
+}
+
+/// AWS Bedrock in one region, authenticated with IAM access keys
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BedrockConfig {
+    pub region: String,
+    pub access_key_id: String,
+    pub secret_access_key: String,
+    /// Set when the keys are temporary credentials
+    pub session_token: Option<String>,
+    pub default_model: String,
+    /// Bedrock model ids, which must be enabled for the account
+    pub models: Vec<String>,
+    pub max_tokens: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GeminiConfig {
+    pub api_key: String,
+    pub base_url: Option<String>,
+    pub default_model: String,
+    pub max_tokens: u32,
+}
+
+/// An Ollama server, or another server with an Ollama-compatible API
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct LocalAIConfig {
     pub enabled: bool,
     pub base_url: String,
+    /// Model ids served locally; ids missing from the model registry are
+    /// registered for all tiers
     pub models: Vec<String>,
+    pub embedding_model: String,
+    /// Ollama tags of model ids that differ from them, e.g.
+    /// `llama2-7b` -> `llama2:7b`
+    pub model_tags: HashMap<String, String>,
+    pub resources: LocalResourceConfig,
+    /// A speech server with OpenAI-compatible `/v1/audio` endpoints, such
+    /// as faster-whisper-server; local audio is disabled when unset
+    pub speech_base_url: Option<String>,
+    pub transcription_model: String,
+    pub speech_model: String,
+    pub speech_voice: String,
+    /// Multimodal model that answers questions about images, e.g. `llava`
+    pub vision_model: String,
+}
+
+/// How local models use the server's hardware. Unset values leave the
+/// choice to Ollama.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalResourceConfig {
+    /// Layers offloaded to the GPU; 0 runs on the CPU only
+    pub num_gpu: Option<u32>,
+    /// GPU holding the shared tensors when a model spans several GPUs
+    pub main_gpu: Option<u32>,
+    /// CPU threads used for generation
+    pub num_thread: Option<u32>,
+    /// Context window in tokens
+    pub num_ctx: Option<u32>,
+    /// How long a model stays loaded after a request, e.g. "5m"; "-1"
+    /// keeps it loaded
+    pub keep_alive: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -50,6 +138,121 @@ pub struct MonitoringConfig {
     pub cost_tracking_enabled: bool,
 }
 
+/// Monthly AI spend limits, read per tenant from license-service quotas
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetConfig {
+    pub enabled: bool,
+    pub license_service_url: String,
+    pub over_budget_action: OverBudgetAction,
+    /// How long a tenant's budget is cached before it is fetched again
+    pub cache_seconds: u64,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum OverBudgetAction {
+    /// Reject requests once the budget is spent
+    Reject,
+    /// Serve requests with a cheaper model once the budget is spent, and
+    /// reject them when no cheaper model has the capability
+    Downgrade,
+}
+
+/// Moderation of AI inputs and outputs. Tenants without a policy of their
+/// own are moderated with these defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationConfig {
+    pub enabled: bool,
+    /// Check content with the OpenAI moderation API, when OpenAI is configured
+    pub provider_check: bool,
+    pub blocked_keywords: Vec<String>,
+    /// Where moderation events are sent as security audit events
+    pub security_service_url: String,
+}
+
+/// Speech-to-text and text-to-speech
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AudioConfig {
+    /// Where audio files transcribed by jobs are downloaded from
+    pub file_service_url: String,
+    /// Largest audio accepted by the synchronous transcription endpoint;
+    /// longer recordings go through transcription jobs
+    pub max_upload_bytes: usize,
+    /// Largest audio file a transcription job accepts
+    pub max_file_bytes: u64,
+    /// Vector collection transcripts are indexed into when a job names none
+    pub transcript_collection: String,
+}
+
+/// Image generation and analysis of images stored in file-service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageConfig {
+    /// Where analyzed images are read from and generated images stored
+    pub file_service_url: String,
+    /// Largest image file an analysis accepts
+    pub max_file_bytes: u64,
+    /// Most images one analysis request can reference
+    pub max_images: usize,
+    /// Longer side of the previews stored with generated images, in pixels
+    pub preview_max_edge: u32,
+}
+
+/// Caching of completions. Tenants without a cache policy of their own are
+/// cached with these defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheConfig {
+    pub enabled: bool,
+    pub ttl_seconds: u64,
+    /// Also serve responses cached for prompts similar to the request's
+    pub semantic_matching: bool,
+    /// Cosine similarity from which a cached prompt counts as the same
+    pub similarity_threshold: f32,
+}
+
+/// Batch classification and summarization of file-service documents
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchConfig {
+    /// Where documents are read from and reports stored
+    pub file_service_url: String,
+    /// Most documents one batch can process
+    pub max_items: usize,
+    /// Largest document a batch reads; larger ones fail as items
+    pub max_file_bytes: u64,
+    /// Most documents processed at once. Batches start here and back off
+    /// when providers rate limit them.
+    pub max_concurrency: usize,
+}
+
+/// Audit log of AI interactions. Tenants without an audit policy of their
+/// own are audited with these defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditConfig {
+    pub enabled: bool,
+    pub store_prompts: bool,
+    pub store_responses: bool,
+    /// Redact every kind of PII from stored prompts and responses
+    pub redact_pii: bool,
+    pub retention_days: u32,
+    /// How often expired records are purged
+    pub purge_interval_seconds: u64,
+}
+
+/// PII detection before prompts leave the platform. Tenants without a PII
+/// policy of their own get these defaults.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PiiConfig {
+    pub enabled: bool,
+    /// Redact or tokenize every kind of pattern-detected PII
+    pub detect_patterns: bool,
+    /// Model entities are detected with; it must be served by a self-hosted
+    /// provider, so texts are not sent out to find their PII
+    pub ner_model: String,
+    /// How long tokenized values can be restored for
+    pub token_ttl_seconds: u64,
+    /// How often expired tokens are purged
+    pub purge_interval_seconds: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SecurityConfig {
     pub jwt_secret: String,
@@ -72,6 +275,12 @@ impl Config {
             .set_default("ai_providers.openai.default_model", "gpt-3.5-turbo")?
             .set_default("ai_providers.openai.max_tokens", 4096)?
             .set_default("ai_providers.openai.temperature", 0.7)?
+            .set_default("ai_providers.openai.embedding_model", "text-embedding-3-small")?
+            .set_default("ai_providers.openai.transcription_model", "whisper-1")?
+            .set_default("ai_providers.openai.speech_model", "tts-1")?
+            .set_default("ai_providers.openai.speech_voice", "alloy")?
+            .set_default("ai_providers.openai.image_model", "dall-e-3")?
+            .set_default("ai_providers.openai.vision_model", "gpt-4o-mini")?
             
             .set_default("ai_providers.anthropic.api_key", "")?
             .set_default("ai_providers.anthropic.default_model", "claude-3-sonnet-20240229")?
@@ -79,6 +288,42 @@ impl Config {
             
             .set_default("ai_providers.local.enabled", false)?
             .set_default("ai_providers.local.base_url", "http://localhost:11434")?
+            .set_default("ai_providers.local.embedding_model", "nomic-embed-text")?
+            .set_default("ai_providers.local.models", vec!["llama2-7b", "mistral-7b"])?
+            .set_default("ai_providers.local.model_tags", config::Map::from([
+                ("llama2-7b".to_string(), "llama2:7b"),
+                ("mistral-7b".to_string(), "mistral:7b"),
+            ]))?
+            .set_default("ai_providers.local.resources.keep_alive", "5m")?
+            .set_default("ai_providers.local.transcription_model", "Systran/faster-whisper-small")?
+            .set_default("ai_providers.local.speech_model", "tts-1")?
+            .set_default("ai_providers.local.speech_voice", "alloy")?
+            .set_default("ai_providers.local.vision_model", "llava")?
+            
+            .set_default("ai_providers.azure_openai.api_key", "")?
+            .set_default("ai_providers.azure_openai.endpoint", "")?
+            .set_default("ai_providers.azure_openai.api_version", "2024-06-01")?
+            .set_default("ai_providers.azure_openai.default_model", "azure-gpt-35-turbo")?
+            .set_default("ai_providers.azure_openai.deployments", config::Map::from([
+                ("azure-gpt-35-turbo".to_string(), "gpt-35-turbo"),
+                ("azure-gpt-4".to_string(), "gpt-4"),
+            ]))?
+            .set_default("ai_providers.azure_openai.max_tokens", 4096)?
+            
+            .set_default("ai_providers.bedrock.region", "us-east-1")?
+            .set_default("ai_providers.bedrock.access_key_id", "")?
+            .set_default("ai_providers.bedrock.secret_access_key", "")?
+            .set_default("ai_providers.bedrock.default_model", "anthropic.claude-3-haiku-20240307-v1:0")?
+            .set_default("ai_providers.bedrock.models", vec![
+                "anthropic.claude-3-haiku-20240307-v1:0",
+                "anthropic.claude-3-sonnet-20240229-v1:0",
+                "meta.llama3-70b-instruct-v1:0",
+            ])?
+            .set_default("ai_providers.bedrock.max_tokens", 4096)?
+            
+            .set_default("ai_providers.gemini.api_key", "")?
+            .set_default("ai_providers.gemini.default_model", "gemini-1.5-flash")?
+            .set_default("ai_providers.gemini.max_tokens", 8192)?
             
             // Monitoring
             .set_default("monitoring.metrics_enabled", true)?
@@ -89,7 +334,56 @@ impl Config {
             // Security
             .set_default("security.jwt_secret", "your-secret-key")?
             .set_default("security.rate_limit_per_minute", 60)?
-            .set_default("security.max_request_size", 1048576)?; // 1MB
+            .set_default("security.max_request_size", 1048576)? // 1MB
+            
+            // Budgets
+            .set_default("budget.enabled", true)?
+            .set_default("budget.license_service_url", "http://localhost:8087")?
+            .set_default("budget.over_budget_action", "reject")?
+            .set_default("budget.cache_seconds", 300)?
+            
+            // Moderation
+            .set_default("moderation.enabled", true)?
+            .set_default("moderation.provider_check", true)?
+            .set_default("moderation.blocked_keywords", vec!["hack", "exploit", "malware", "virus"])?
+            .set_default("moderation.security_service_url", "http://localhost:8087")?
+            
+            // Audio
+            .set_default("audio.file_service_url", "http://localhost:8083")?
+            .set_default("audio.max_upload_bytes", 10485760)? // 10MB
+            .set_default("audio.max_file_bytes", 26214400)? // 25MB, the Whisper API limit
+            .set_default("audio.transcript_collection", "transcripts")?
+            
+            // Images
+            .set_default("images.file_service_url", "http://localhost:8083")?
+            .set_default("images.max_file_bytes", 20971520)? // 20MB, the OpenAI vision limit
+            .set_default("images.max_images", 10)?
+            .set_default("images.preview_max_edge", 256)?
+            
+            // Response cache
+            .set_default("cache.enabled", false)?
+            .set_default("cache.ttl_seconds", 86400)? // 1 day
+            .set_default("cache.semantic_matching", false)?
+            .set_default("cache.similarity_threshold", 0.95)?
+            
+            // Batch processing
+            .set_default("batch.file_service_url", "http://localhost:8083")?
+            .set_default("batch.max_items", 1000)?
+            .set_default("batch.max_file_bytes", 1048576)? // 1MB of text
+            .set_default("batch.max_concurrency", 4)?
+            
+            // Audit log
+            .set_default("audit.enabled", true)?
+            .set_default("audit.store_prompts", true)?
+            .set_default("audit.store_responses", true)?
+            .set_default("audit.redact_pii", true)?
+            .set_default("audit.retention_days", 90)?
+            .set_default("audit.purge_interval_seconds", 3600)?
+            .set_default("pii.enabled", false)?
+            .set_default("pii.detect_patterns", true)?
+            .set_default("pii.ner_model", "llama2-7b")?
+            .set_default("pii.token_ttl_seconds", 604800)? // 7 days
+            .set_default("pii.purge_interval_seconds", 3600)?;
 
         // Override with environment variables
         cfg = cfg.add_source(config::Environment::with_prefix("AI_SERVICE"));
@@ -111,6 +405,48 @@ impl Config {
             cfg = cfg.set_override("ai_providers.openai.api_key", openai_key)?;
         }
         
+        if let Ok(azure_key) = env::var("AZURE_OPENAI_API_KEY") {
+            cfg = cfg.set_override("ai_providers.azure_openai.api_key", azure_key)?;
+        }
+        
+        if let Ok(azure_endpoint) = env::var("AZURE_OPENAI_ENDPOINT") {
+            cfg = cfg.set_override("ai_providers.azure_openai.endpoint", azure_endpoint)?;
+        }
+        
+        if let Ok(aws_region) = env::var("AWS_REGION") {
+            cfg = cfg.set_override("ai_providers.bedrock.region", aws_region)?;
+        }
+        
+        if let Ok(aws_access_key_id) = env::var("AWS_ACCESS_KEY_ID") {
+            cfg = cfg.set_override("ai_providers.bedrock.access_key_id", aws_access_key_id)?;
+        }
+        
+        if let Ok(aws_secret_access_key) = env::var("AWS_SECRET_ACCESS_KEY") {
+            cfg = cfg.set_override("ai_providers.bedrock.secret_access_key", aws_secret_access_key)?;
+        }
+        
+        if let Ok(aws_session_token) = env::var("AWS_SESSION_TOKEN") {
+            cfg = cfg.set_override("ai_providers.bedrock.session_token", aws_session_token)?;
+        }
+        
+        if let Ok(gemini_key) = env::var("GEMINI_API_KEY") {
+            cfg = cfg.set_override("ai_providers.gemini.api_key", gemini_key)?;
+        }
+        
+        if let Ok(license_service_url) = env::var("LICENSE_SERVICE_URL") {
+            cfg = cfg.set_override("budget.license_service_url", license_service_url)?;
+        }
+        
+        if let Ok(security_service_url) = env::var("SECURITY_SERVICE_URL") {
+            cfg = cfg.set_override("moderation.security_service_url", security_service_url)?;
+        }
+        
+        if let Ok(file_service_url) = env::var("FILE_SERVICE_URL") {
+            cfg = cfg.set_override("audio.file_service_url", file_service_url.clone())?;
+            cfg = cfg.set_override("images.file_service_url", file_service_url.clone())?;
+            cfg = cfg.set_override("batch.file_service_url", file_service_url)?;
+        }
+        
         if let Ok(anthropic_key) = env::var("ANTHROPIC_API_KEY") {
             cfg = cfg.set_override("ai_providers.anthropic.api_key", anthropic_key)?;
         }
@@ -0,0 +1,110 @@
+use crate::config::LocalAIConfig;
+use crate::embeddings::{check_embeddings, EmbeddingProvider};
+use crate::error::{AIError, AIResult};
+use crate::types::*;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+struct LocalEmbeddingRequest<'a> {
+    model: &'a str,
+    input: &'a [String],
+}
+
+#[derive(Debug, Deserialize)]
+struct LocalEmbeddingResponse {
+    data: Vec<LocalEmbedding>,
+    usage: Option<LocalEmbeddingUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LocalEmbedding {
+    index: u32,
+    embedding: Vec<f32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct LocalEmbeddingUsage {
+    prompt_tokens: u32,
+    total_tokens: u32,
+}
+
+/// Embeddings from a local server exposing the OpenAI-compatible
+/// `/v1/embeddings` endpoint
+pub struct LocalEmbeddingProvider {
+    client: Client,
+    config: LocalAIConfig,
+}
+
+impl LocalEmbeddingProvider {
+    pub fn new(config: &LocalAIConfig) -> Self {
+        Self {
+            client: Client::new(),
+            config: config.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for LocalEmbeddingProvider {
+    async fn embed(&self, request: &EmbeddingRequest) -> AIResult<EmbeddingResult> {
+        let model = request.model.clone().unwrap_or_else(|| self.config.embedding_model.clone());
+
+        let response = self
+            .client
+            .post(&format!("{}/v1/embeddings", self.config.base_url))
+            .header("Content-Type", "application/json")
+            .json(&LocalEmbeddingRequest {
+                model: &model,
+                input: &request.inputs,
+            })
+            .send()
+            .await
+            .map_err(|e| AIError::HttpClient(e))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(AIError::AIProvider(format!("Local AI error: {}", error_text)));
+        }
+
+        let response = response
+            .json::<LocalEmbeddingResponse>()
+            .await
+            .map_err(|e| AIError::AIProvider(format!("Failed to parse Local AI response: {}", e)))?;
+
+        let mut data = response.data;
+        data.sort_by_key(|embedding| embedding.index);
+        let embeddings: Vec<Vec<f32>> = data.into_iter().map(|embedding| embedding.embedding).collect();
+        let dimensions = check_embeddings(request.inputs.len(), &embeddings)?;
+
+        // Local servers do not always report usage
+        let (prompt_tokens, total_tokens) = match response.usage {
+            Some(usage) => (usage.prompt_tokens, usage.total_tokens),
+            None => {
+                let estimated = request.inputs.iter().map(|input| input.len() as u32 / 4).sum();
+                (estimated, estimated)
+            }
+        };
+
+        Ok(EmbeddingResult {
+            embeddings,
+            model,
+            dimensions,
+            usage: TokenUsage {
+                prompt_tokens,
+                completion_tokens: 0,
+                total_tokens,
+                estimated_cost: 0.0, // Local models have no API cost
+            },
+        })
+    }
+
+    fn default_model(&self) -> &str {
+        &self.config.embedding_model
+    }
+
+    fn get_provider_type(&self) -> crate::types::AIProvider {
+        crate::types::AIProvider::Local
+    }
+}
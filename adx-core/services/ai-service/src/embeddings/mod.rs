@@ -0,0 +1,86 @@
+pub mod openai;
+pub mod local;
+pub mod vector_store;
+
+pub use vector_store::{PgVectorStore, VectorStore};
+
+use crate::error::{AIError, AIResult};
+use crate::types::*;
+use async_trait::async_trait;
+
+#[async_trait]
+pub trait EmbeddingProvider: Send + Sync {
+    async fn embed(&self, request: &EmbeddingRequest) -> AIResult<EmbeddingResult>;
+    fn default_model(&self) -> &str;
+    fn get_provider_type(&self) -> crate::types::AIProvider;
+}
+
+pub struct EmbeddingProviderManager {
+    openai: Option<openai::OpenAIEmbeddingProvider>,
+    local: Option<local::LocalEmbeddingProvider>,
+}
+
+impl EmbeddingProviderManager {
+    pub fn new(config: &crate::config::AIProvidersConfig) -> Self {
+        let openai = if !config.openai.api_key.is_empty() {
+            Some(openai::OpenAIEmbeddingProvider::new(&config.openai))
+        } else {
+            None
+        };
+
+        let local = if config.local.enabled {
+            Some(local::LocalEmbeddingProvider::new(&config.local))
+        } else {
+            None
+        };
+
+        Self { openai, local }
+    }
+
+    pub fn get_provider(&self, provider_type: &crate::types::AIProvider) -> AIResult<&dyn EmbeddingProvider> {
+        match provider_type {
+            crate::types::AIProvider::OpenAI => {
+                self.openai.as_ref()
+                    .map(|p| p as &dyn EmbeddingProvider)
+                    .ok_or_else(|| AIError::AIProvider("OpenAI embeddings not configured".to_string()))
+            }
+            crate::types::AIProvider::Local => {
+                self.local.as_ref()
+                    .map(|p| p as &dyn EmbeddingProvider)
+                    .ok_or_else(|| AIError::AIProvider("Local embeddings not configured".to_string()))
+            }
+            crate::types::AIProvider::Anthropic => {
+                Err(AIError::ModelNotAvailable("Anthropic does not provide embeddings".to_string()))
+            }
+            provider => {
+                Err(AIError::ModelNotAvailable(format!("{:?} embeddings are not supported", provider)))
+            }
+        }
+    }
+
+    /// The provider used when a request does not name one
+    pub fn default_provider(&self) -> AIResult<&dyn EmbeddingProvider> {
+        self.openai.as_ref()
+            .map(|p| p as &dyn EmbeddingProvider)
+            .or_else(|| self.local.as_ref().map(|p| p as &dyn EmbeddingProvider))
+            .ok_or_else(|| AIError::AIProvider("No embedding provider configured".to_string()))
+    }
+}
+
+/// Check a provider returned one vector of one size per input
+pub(crate) fn check_embeddings(inputs: usize, embeddings: &[Vec<f32>]) -> AIResult<usize> {
+    if embeddings.len() != inputs {
+        return Err(AIError::AIProvider(format!(
+            "Expected {} embeddings, provider returned {}",
+            inputs,
+            embeddings.len()
+        )));
+    }
+
+    let dimensions = embeddings.first().map(Vec::len).unwrap_or(0);
+    if embeddings.iter().any(|embedding| embedding.len() != dimensions) {
+        return Err(AIError::AIProvider("Provider returned embeddings of different sizes".to_string()));
+    }
+
+    Ok(dimensions)
+}
@@ -0,0 +1,82 @@
+use crate::config::OpenAIConfig;
+use crate::embeddings::{check_embeddings, EmbeddingProvider};
+use crate::error::{AIError, AIResult};
+use crate::types::*;
+use async_openai::{
+    types::{CreateEmbeddingRequest, EmbeddingInput},
+    Client,
+};
+use async_trait::async_trait;
+
+pub struct OpenAIEmbeddingProvider {
+    client: Client<async_openai::config::OpenAIConfig>,
+    config: OpenAIConfig,
+}
+
+impl OpenAIEmbeddingProvider {
+    pub fn new(config: &OpenAIConfig) -> Self {
+        let mut openai_config = async_openai::config::OpenAIConfig::new()
+            .with_api_key(&config.api_key);
+
+        if let Some(base_url) = &config.base_url {
+            openai_config = openai_config.with_api_base(base_url);
+        }
+
+        Self {
+            client: Client::with_config(openai_config),
+            config: config.clone(),
+        }
+    }
+}
+
+/// Price per token of the OpenAI embedding models
+fn cost_per_token(model: &str) -> f64 {
+    match model {
+        "text-embedding-3-large" => 0.00000013,
+        "text-embedding-ada-002" => 0.0000001,
+        _ => 0.00000002, // text-embedding-3-small
+    }
+}
+
+#[async_trait]
+impl EmbeddingProvider for OpenAIEmbeddingProvider {
+    async fn embed(&self, request: &EmbeddingRequest) -> AIResult<EmbeddingResult> {
+        let model = request.model.clone().unwrap_or_else(|| self.config.embedding_model.clone());
+
+        let response = self.client
+            .embeddings()
+            .create(CreateEmbeddingRequest {
+                model: model.clone(),
+                input: EmbeddingInput::StringArray(request.inputs.clone()),
+                encoding_format: None,
+                user: Some(request.context.user_id.clone()),
+            })
+            .await
+            .map_err(|e| AIError::AIProvider(format!("OpenAI API error: {}", e)))?;
+
+        let mut data = response.data;
+        data.sort_by_key(|embedding| embedding.index);
+        let embeddings: Vec<Vec<f32>> = data.into_iter().map(|embedding| embedding.embedding).collect();
+        let dimensions = check_embeddings(request.inputs.len(), &embeddings)?;
+
+        Ok(EmbeddingResult {
+            embeddings,
+            dimensions,
+            usage: TokenUsage {
+                prompt_tokens: response.usage.prompt_tokens,
+                completion_tokens: 0,
+                total_tokens: response.usage.total_tokens,
+                estimated_cost: response.usage.total_tokens as f64 * cost_per_token(&model),
+            },
+            model,
+        })
+    }
+
+    fn default_model(&self) -> &str {
+        &self.config.embedding_model
+    }
+
+    fn get_provider_type(&self) -> crate::types::AIProvider {
+        crate::types::AIProvider::OpenAI
+    }
+}
@@ -0,0 +1,150 @@
+use crate::error::{AIError, AIResult};
+use crate::types::*;
+use async_trait::async_trait;
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+
+/// Tenant-scoped storage of embedded documents. Documents live in named
+/// collections per tenant, and are only compared with vectors from the
+/// embedding model they were stored with.
+#[async_trait]
+pub trait VectorStore: Send + Sync {
+    /// Insert the documents, replacing documents with the same ids
+    async fn upsert(
+        &self,
+        tenant_id: &str,
+        collection: &str,
+        model: &str,
+        documents: &[VectorDocument],
+    ) -> AIResult<()>;
+
+    /// The `top_k` documents closest to `embedding`, optionally only those
+    /// whose metadata contains `filter`
+    async fn query(
+        &self,
+        tenant_id: &str,
+        collection: &str,
+        model: &str,
+        embedding: &[f32],
+        top_k: usize,
+        filter: Option<&serde_json::Value>,
+    ) -> AIResult<Vec<VectorMatch>>;
+
+    /// Delete documents by id, returning how many existed
+    async fn delete(&self, tenant_id: &str, collection: &str, ids: &[String]) -> AIResult<u64>;
+}
+
+/// Vector store on Postgres with the pgvector extension
+pub struct PgVectorStore {
+    db_pool: Arc<PgPool>,
+}
+
+impl PgVectorStore {
+    pub fn new(db_pool: Arc<PgPool>) -> Self {
+        Self { db_pool }
+    }
+}
+
+#[async_trait]
+impl VectorStore for PgVectorStore {
+    async fn upsert(
+        &self,
+        tenant_id: &str,
+        collection: &str,
+        model: &str,
+        documents: &[VectorDocument],
+    ) -> AIResult<()> {
+        let mut tx = self.db_pool.begin().await?;
+
+        for document in documents {
+            sqlx::query(
+                r#"
+                INSERT INTO ai_embeddings (tenant_id, collection, id, model, dimensions, embedding, content, metadata)
+                VALUES ($1, $2, $3, $4, $5, $6::vector, $7, $8)
+                ON CONFLICT (tenant_id, collection, id) DO UPDATE
+                SET model = EXCLUDED.model,
+                    dimensions = EXCLUDED.dimensions,
+                    embedding = EXCLUDED.embedding,
+                    content = EXCLUDED.content,
+                    metadata = EXCLUDED.metadata
+                "#,
+            )
+            .bind(tenant_id)
+            .bind(collection)
+            .bind(&document.id)
+            .bind(model)
+            .bind(document.embedding.len() as i32)
+            .bind(vector_literal(&document.embedding))
+            .bind(&document.content)
+            .bind(&document.metadata)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn query(
+        &self,
+        tenant_id: &str,
+        collection: &str,
+        model: &str,
+        embedding: &[f32],
+        top_k: usize,
+        filter: Option<&serde_json::Value>,
+    ) -> AIResult<Vec<VectorMatch>> {
+        // `<=>` is the cosine distance; documents of another size cannot be
+        // compared and are excluded with the dimensions check
+        let rows = sqlx::query(
+            r#"
+            SELECT id, content, metadata, 1 - (embedding <=> $4::vector) AS score
+            FROM ai_embeddings
+            WHERE tenant_id = $1 AND collection = $2 AND model = $3 AND dimensions = $5
+              AND ($6::jsonb IS NULL OR metadata @> $6::jsonb)
+            ORDER BY embedding <=> $4::vector
+            LIMIT $7
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(collection)
+        .bind(model)
+        .bind(vector_literal(embedding))
+        .bind(embedding.len() as i32)
+        .bind(filter)
+        .bind(top_k as i64)
+        .fetch_all(&*self.db_pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(VectorMatch {
+                    id: row.try_get("id")?,
+                    content: row.try_get("content")?,
+                    metadata: row.try_get("metadata")?,
+                    score: row.try_get::<f64, _>("score")? as f32,
+                })
+            })
+            .collect::<Result<_, sqlx::Error>>()
+            .map_err(AIError::Database)
+    }
+
+    async fn delete(&self, tenant_id: &str, collection: &str, ids: &[String]) -> AIResult<u64> {
+        let result = sqlx::query(
+            "DELETE FROM ai_embeddings WHERE tenant_id = $1 AND collection = $2 AND id = ANY($3)",
+        )
+        .bind(tenant_id)
+        .bind(collection)
+        .bind(ids)
+        .execute(&*self.db_pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+}
+
+/// pgvector's text form, `[1,2,3]`, which the queries cast to `vector`
+pub(crate) fn vector_literal(embedding: &[f32]) -> String {
+    let values: Vec<String> = embedding.iter().map(|value| value.to_string()).collect();
+    format!("[{}]", values.join(","))
+}
@@ -0,0 +1,208 @@
+use crate::error::{AIError, AIResult};
+use crate::types::*;
+use chrono::{DateTime, Utc};
+use reqwest::{multipart, Response, StatusCode};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// The parts of a file-service file that AI requests need
+#[derive(Debug, Clone, Deserialize)]
+pub struct StoredFile {
+    pub filename: String,
+    pub mime_type: String,
+    pub file_size: i64,
+}
+
+/// A file as file-service lists it
+#[derive(Debug, Clone, Deserialize)]
+pub struct ListedFile {
+    pub id: Uuid,
+    pub filename: String,
+    pub mime_type: String,
+    pub file_size: i64,
+    /// "Ready" once the content is uploaded
+    pub status: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileList {
+    files: Vec<ListedFile>,
+    total: i64,
+}
+
+/// Files file-service returns per page at most
+const LIST_PAGE_SIZE: usize = 100;
+
+#[derive(Debug, Deserialize)]
+struct FileDownload {
+    download_url: String,
+}
+
+#[derive(Debug, Serialize)]
+struct CreateFile<'a> {
+    filename: &'a str,
+    mime_type: &'a str,
+    file_size: i64,
+    metadata: &'a serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreatedFile {
+    file_id: Uuid,
+}
+
+/// Reads and writes file-service files on behalf of a tenant's user
+#[derive(Clone)]
+pub struct FileServiceClient {
+    client: reqwest::Client,
+    file_service_url: String,
+}
+
+impl FileServiceClient {
+    pub fn new(file_service_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            file_service_url: file_service_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub async fn get(&self, context: &RequestContext, file_id: Uuid) -> AIResult<StoredFile> {
+        let response = self
+            .client
+            .get(format!("{}/api/v1/files/{}", self.file_service_url, file_id))
+            .header("X-Tenant-ID", &context.tenant_id)
+            .header("X-User-ID", &context.user_id)
+            .send()
+            .await?;
+
+        Ok(check_status(response, file_id).await?.json().await?)
+    }
+
+    /// The first `limit` of the user's files that `keep` accepts
+    pub async fn list(
+        &self,
+        context: &RequestContext,
+        limit: usize,
+        keep: impl Fn(&ListedFile) -> bool,
+    ) -> AIResult<Vec<ListedFile>> {
+        let mut kept = Vec::new();
+        for page in 1.. {
+            let response = self
+                .client
+                .get(format!("{}/api/v1/files", self.file_service_url))
+                .query(&[("page", page), ("per_page", LIST_PAGE_SIZE)])
+                .header("X-Tenant-ID", &context.tenant_id)
+                .header("X-User-ID", &context.user_id)
+                .send()
+                .await?;
+            if !response.status().is_success() {
+                return Err(AIError::Internal(format!("File service returned {} listing files", response.status())));
+            }
+            let list: FileList = response.json().await?;
+
+            let listed = list.files.len();
+            kept.extend(list.files.into_iter().filter(|file| keep(file)));
+            if kept.len() >= limit || listed < LIST_PAGE_SIZE || (page * LIST_PAGE_SIZE) as i64 >= list.total {
+                break;
+            }
+        }
+
+        kept.truncate(limit);
+        Ok(kept)
+    }
+
+    /// The file and its content. Files over `max_bytes` are refused before
+    /// they are downloaded.
+    pub async fn download(&self, context: &RequestContext, file_id: Uuid, max_bytes: u64) -> AIResult<(StoredFile, Vec<u8>)> {
+        let file = self.get(context, file_id).await?;
+        if file.file_size as u64 > max_bytes {
+            return Err(AIError::Validation(format!(
+                "File {} is {} bytes; at most {} bytes can be processed",
+                file_id, file.file_size, max_bytes
+            )));
+        }
+
+        let response = self
+            .client
+            .get(format!("{}/api/v1/files/{}/download", self.file_service_url, file_id))
+            .header("X-Tenant-ID", &context.tenant_id)
+            .header("X-User-ID", &context.user_id)
+            .send()
+            .await?;
+        let download: FileDownload = check_status(response, file_id).await?.json().await?;
+
+        let response = self.client.get(&download.download_url).send().await?;
+        let content = check_status(response, file_id).await?.bytes().await?;
+        if content.len() as u64 > max_bytes {
+            return Err(AIError::Validation(format!(
+                "File {} is larger than {} bytes",
+                file_id, max_bytes
+            )));
+        }
+
+        Ok((file, content.to_vec()))
+    }
+
+    /// Store `content` as a new file owned by the user
+    pub async fn upload(
+        &self,
+        context: &RequestContext,
+        filename: &str,
+        mime_type: &str,
+        content: Vec<u8>,
+        metadata: &serde_json::Value,
+    ) -> AIResult<Uuid> {
+        let response = self
+            .client
+            .post(format!("{}/api/v1/files", self.file_service_url))
+            .header("X-Tenant-ID", &context.tenant_id)
+            .header("X-User-ID", &context.user_id)
+            .json(&CreateFile {
+                filename,
+                mime_type,
+                file_size: content.len() as i64,
+                metadata,
+            })
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(AIError::Internal(format!(
+                "File service returned {} creating {}",
+                response.status(),
+                filename
+            )));
+        }
+        let created: CreatedFile = response.json().await?;
+
+        let part = multipart::Part::bytes(content)
+            .file_name(filename.to_string())
+            .mime_str(mime_type)?;
+        let response = self
+            .client
+            .post(format!("{}/api/v1/files/{}/upload", self.file_service_url, created.file_id))
+            .header("X-Tenant-ID", &context.tenant_id)
+            .header("X-User-ID", &context.user_id)
+            .multipart(multipart::Form::new().part("file", part))
+            .send()
+            .await?;
+        check_status(response, created.file_id).await?;
+
+        Ok(created.file_id)
+    }
+}
+
+async fn check_status(response: Response, file_id: Uuid) -> AIResult<Response> {
+    match response.status() {
+        status if status.is_success() => Ok(response),
+        StatusCode::NOT_FOUND | StatusCode::FORBIDDEN => {
+            Err(AIError::NotFound(format!("File {} not found", file_id)))
+        }
+        StatusCode::CONFLICT => Err(AIError::Validation(format!("File {} is not uploaded yet", file_id))),
+        StatusCode::UNSUPPORTED_MEDIA_TYPE => Err(AIError::Validation(format!(
+            "The tenant's file type policy does not permit file {}",
+            file_id
+        ))),
+        status => Err(AIError::Internal(format!("File service returned {}", status))),
+    }
+}
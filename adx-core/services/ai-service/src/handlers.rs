@@ -1,14 +1,29 @@
 use crate::error::{AIError, AIResult};
-use crate::services::{AIService, HealthMonitor, UsageTracker};
+use crate::moderation::Moderator;
+use crate::providers::local::{LoadedModel, LocalModel, ModelPull};
+use crate::services::{
+    budget::BudgetStatus, embedding_service::UpsertResult, prompt_templates::PromptTemplateInput,
+    response_cache::{CacheMode, CacheRequest}, stream_sessions::StreamUpdate, AIService, AudioService, AuditLog,
+    BatchProcessor, BudgetEnforcer, EmbeddingService, HealthMonitor, ImageService, PromptTemplateStore, ProviderRouter,
+    ResponseCache, StreamSessionStore, UsageTracker,
+};
 use crate::types::*;
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    extract::{Multipart, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{
+        sse::{Event, KeepAlive, Sse},
+        IntoResponse, Json, Response,
+    },
     Extension,
 };
 use chrono::{DateTime, Utc};
+use futures::{stream, Stream, StreamExt};
 use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::convert::Infallible;
+use std::future::Future;
+use uuid::Uuid;
 // use shared::middleware::TenantContext; // Commented out until shared crate is available
 
 // Temporary TenantContext for compilation
@@ -26,6 +41,17 @@ pub struct AppStateInner {
     pub ai_service: Arc<AIService>,
     pub usage_tracker: Arc<UsageTracker>,
     pub health_monitor: Arc<HealthMonitor>,
+    pub stream_sessions: Arc<StreamSessionStore>,
+    pub embedding_service: Arc<EmbeddingService>,
+    pub budget: Arc<BudgetEnforcer>,
+    pub prompt_templates: Arc<PromptTemplateStore>,
+    pub moderator: Arc<Moderator>,
+    pub router: Arc<ProviderRouter>,
+    pub audio: Arc<AudioService>,
+    pub images: Arc<ImageService>,
+    pub response_cache: Arc<ResponseCache>,
+    pub batches: Arc<BatchProcessor>,
+    pub audit: Arc<AuditLog>,
 }
 
 // Health check endpoint
@@ -74,11 +100,22 @@ pub async fn get_models_for_capability(
 // Generate text endpoint
 #[derive(Debug, Deserialize)]
 pub struct GenerateTextRequest {
-    pub prompt: String,
+    /// Required unless `template` is given
+    pub prompt: Option<String>,
+    pub template: Option<PromptInvocation>,
     pub model: Option<String>,
     pub parameters: Option<AIParameters>,
 }
 
+/// A prompt template and the values of its variables
+#[derive(Debug, Deserialize)]
+pub struct PromptInvocation {
+    #[serde(flatten)]
+    pub reference: PromptReference,
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+}
+
 #[derive(Debug, Serialize)]
 pub struct GenerateTextResponse {
     pub id: String,
@@ -89,10 +126,60 @@ pub struct GenerateTextResponse {
 }
 
 pub async fn generate_text(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    headers: HeaderMap,
+    Json(request): Json<GenerateTextRequest>,
+) -> Result<Response, AIError> {
+    let context = RequestContext {
+        tenant_id: tenant_context.tenant_id.clone(),
+        user_id: tenant_context.user_id.clone(),
+        session_id: None,
+        workflow_id: None,
+        activity_id: None,
+    };
+    
+    let (prompt, model, parameters) = resolve_prompt(&state, &tenant_context, request).await?;
+    let prompt = state.moderator.moderate(&context, ModerationStage::Input, &prompt).await?.text;
+    let model = resolve_model(&state, &tenant_context, model, &AICapability::TextGeneration).await?;
+    let cache_request = CacheRequest {
+        context: &context,
+        capability: AICapability::TextGeneration,
+        model: &model,
+        prompt: &prompt,
+        parameters: serde_json::to_value(&parameters)?,
+    };
+    
+    cached_response(&state, &headers, cache_request, async {
+        let ai_request = state.ai_service.create_ai_request(
+            prompt.clone(),
+            model.clone(),
+            parameters,
+            context.clone(),
+        ).await?;
+        
+        let requested_at = Utc::now();
+        let response = state.ai_service.process_ai_request(ai_request).await?;
+        record_usage(&state.usage_tracker, context.clone(), &response.model, AICapability::TextGeneration, &response.usage, requested_at).await;
+        let generated_text = state.moderator.moderate(&context, ModerationStage::Output, &response.content).await?.text;
+        
+        Ok(GenerateTextResponse {
+            id: response.id,
+            generated_text,
+            model: response.model,
+            usage: response.usage,
+            created_at: response.created_at,
+        })
+    }).await
+}
+
+// Streaming generation endpoint. Tokens are relayed as server-sent events
+// as the provider produces them; nothing is persisted.
+pub async fn generate_text_stream(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
     Json(request): Json<GenerateTextRequest>,
-) -> Result<Json<GenerateTextResponse>, AIError> {
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AIError> {
     let context = RequestContext {
         tenant_id: tenant_context.tenant_id.clone(),
         user_id: tenant_context.user_id.clone(),
@@ -101,22 +188,760 @@ pub async fn generate_text(
         activity_id: None,
     };
     
+    let (prompt, model, parameters) = resolve_prompt(&state, &tenant_context, request).await?;
+    let prompt = state.moderator.moderate(&context, ModerationStage::Input, &prompt).await?.text;
+    let model = resolve_model(&state, &tenant_context, model, &AICapability::TextGeneration).await?;
     let ai_request = state.ai_service.create_ai_request(
-        request.prompt,
-        request.model.unwrap_or_else(|| "gpt-3.5-turbo".to_string()),
-        request.parameters.unwrap_or_default(),
-        context,
+        prompt,
+        model.clone(),
+        parameters,
+        context.clone(),
     ).await?;
     
-    let response = state.ai_service.process_ai_request(ai_request).await?;
+    let requested_at = Utc::now();
+    let chunks = state.ai_service.stream_ai_request(ai_request).await?;
     
-    Ok(Json(GenerateTextResponse {
-        id: response.id,
-        generated_text: response.content,
-        model: response.model,
-        usage: response.usage,
-        created_at: response.created_at,
-    }))
+    let usage_tracker = state.usage_tracker.clone();
+    let moderator = state.moderator.clone();
+    let mut output = String::new();
+    let events = chunks.flat_map(move |chunk| {
+        let mut events = Vec::new();
+        match chunk {
+            Ok(chunk) => {
+                if !chunk.delta.is_empty() {
+                    events.push(token_event(output.len(), &chunk.delta));
+                    output.push_str(&chunk.delta);
+                }
+                if let Some(finish_reason) = chunk.finish_reason {
+                    events.push(done_event(&finish_reason, chunk.usage.as_ref()));
+                    flag_streamed_output(moderator.clone(), context.clone(), std::mem::take(&mut output));
+                }
+                if let Some(usage) = chunk.usage {
+                    let usage_tracker = usage_tracker.clone();
+                    let (context, model) = (context.clone(), model.clone());
+                    tokio::spawn(async move {
+                        record_usage(&usage_tracker, context, &model, AICapability::TextGeneration, &usage, requested_at).await;
+                    });
+                }
+            }
+            Err(e) => events.push(error_event(&e.to_string())),
+        }
+        stream::iter(events.into_iter().map(Ok))
+    });
+    
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+// Start a persisted stream. Generation continues if the client disconnects,
+// and can be followed or resumed with GET /api/v1/generate/streams/:stream_id.
+pub async fn start_text_stream(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(request): Json<GenerateTextRequest>,
+) -> Result<(StatusCode, Json<StreamSession>), AIError> {
+    let context = RequestContext {
+        tenant_id: tenant_context.tenant_id.clone(),
+        user_id: tenant_context.user_id.clone(),
+        session_id: None,
+        workflow_id: None,
+        activity_id: None,
+    };
+    
+    let (prompt, model, parameters) = resolve_prompt(&state, &tenant_context, request).await?;
+    let prompt = state.moderator.moderate(&context, ModerationStage::Input, &prompt).await?.text;
+    let model = resolve_model(&state, &tenant_context, model, &AICapability::TextGeneration).await?;
+    let ai_request = state.ai_service.create_ai_request(
+        prompt,
+        model,
+        parameters,
+        context.clone(),
+    ).await?;
+    
+    let session = state.stream_sessions
+        .create(Uuid::new_v4(), &ai_request.context, &ai_request.model)
+        .await?;
+    let requested_at = Utc::now();
+    let chunks = state.ai_service.stream_ai_request(ai_request).await?;
+    
+    let stream_sessions = state.stream_sessions.clone();
+    let usage_tracker = state.usage_tracker.clone();
+    let moderator = state.moderator.clone();
+    let recording = session.clone();
+    tokio::spawn(async move {
+        match stream_sessions.record(&recording, chunks).await {
+            Ok(session) => {
+                if let Some(usage) = &session.usage {
+                    record_usage(&usage_tracker, context.clone(), &session.model, AICapability::TextGeneration, usage, requested_at).await;
+                }
+                flag_streamed_output(moderator, context, session.output);
+            }
+            Err(e) => tracing::warn!("Stream {} failed: {}", recording.id, e),
+        }
+    });
+    
+    Ok((StatusCode::ACCEPTED, Json(session)))
+}
+
+// Follow a persisted stream. Reconnecting clients resume after the last
+// event they received via the Last-Event-ID header, or from `offset`.
+#[derive(Debug, Deserialize)]
+pub struct FollowStreamQuery {
+    offset: Option<usize>,
+}
+
+pub async fn follow_text_stream(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(stream_id): Path<Uuid>,
+    Query(query): Query<FollowStreamQuery>,
+    headers: HeaderMap,
+) -> Result<Sse<impl Stream<Item = Result<Event, Infallible>>>, AIError> {
+    // Fails for streams of other tenants
+    state.stream_sessions.get(&tenant_context.tenant_id, stream_id).await?;
+    
+    let offset = headers
+        .get("last-event-id")
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.parse().ok())
+        .or(query.offset)
+        .unwrap_or(0);
+    
+    let events = state.stream_sessions.clone()
+        .resume(tenant_context.tenant_id, stream_id, offset)
+        .map(|update| Ok(match update {
+            StreamUpdate::Delta { offset, text } => token_event(offset, &text),
+            StreamUpdate::Completed { finish_reason, usage } => done_event(&finish_reason, usage.as_ref()),
+            StreamUpdate::Failed { error } => error_event(&error),
+        }));
+    
+    Ok(Sse::new(events).keep_alive(KeepAlive::default()))
+}
+
+// Token events carry the byte offset of the output after the token, so the
+// last one received is where a reconnecting client resumes.
+fn token_event(offset: usize, delta: &str) -> Event {
+    Event::default()
+        .event("token")
+        .id((offset + delta.len()).to_string())
+        .data(json!({ "delta": delta }).to_string())
+}
+
+fn done_event(finish_reason: &FinishReason, usage: Option<&TokenUsage>) -> Event {
+    Event::default()
+        .event("done")
+        .data(json!({ "finish_reason": finish_reason, "usage": usage }).to_string())
+}
+
+fn error_event(message: &str) -> Event {
+    Event::default()
+        .event("error")
+        .data(json!({ "message": message }).to_string())
+}
+
+// Embeddings endpoint
+#[derive(Debug, Deserialize)]
+pub struct CreateEmbeddingsRequest {
+    pub inputs: Vec<String>,
+    pub model: Option<String>,
+    pub provider: Option<AIProvider>,
+}
+
+pub async fn create_embeddings(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(request): Json<CreateEmbeddingsRequest>,
+) -> Result<Json<EmbeddingResult>, AIError> {
+    state.budget.ensure_within_budget(&tenant_context.tenant_id).await?;
+    
+    let provider = state.router.route_provider(&tenant_context.tenant_id, request.provider, "embedding").await?;
+    let requested_at = Utc::now();
+    let result = state.embedding_service.embed(
+        request.inputs,
+        request.model,
+        provider.as_ref(),
+        request_context(&tenant_context),
+    ).await?;
+    record_usage(&state.usage_tracker, request_context(&tenant_context), &result.model, AICapability::TextEmbedding, &result.usage, requested_at).await;
+    
+    Ok(Json(result))
+}
+
+// Vector store endpoints. Collections are scoped to the caller's tenant.
+#[derive(Debug, Deserialize)]
+pub struct UpsertVectorsRequest {
+    pub documents: Vec<UpsertDocument>,
+    pub model: Option<String>,
+    pub provider: Option<AIProvider>,
+}
+
+pub async fn upsert_vectors(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(collection): Path<String>,
+    Json(request): Json<UpsertVectorsRequest>,
+) -> Result<Json<UpsertResult>, AIError> {
+    state.budget.ensure_within_budget(&tenant_context.tenant_id).await?;
+    
+    let provider = state.router.route_provider(&tenant_context.tenant_id, request.provider, "embedding").await?;
+    let requested_at = Utc::now();
+    let result = state.embedding_service.upsert(
+        &collection,
+        request.documents,
+        request.model,
+        provider.as_ref(),
+        request_context(&tenant_context),
+    ).await?;
+    record_usage(&state.usage_tracker, request_context(&tenant_context), &result.model, AICapability::TextEmbedding, &result.usage, requested_at).await;
+    
+    Ok(Json(result))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct QueryVectorsRequest {
+    pub query: String,
+    pub top_k: Option<usize>,
+    /// Only match documents whose metadata contains this object
+    pub filter: Option<serde_json::Value>,
+    pub model: Option<String>,
+    pub provider: Option<AIProvider>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryVectorsResponse {
+    pub matches: Vec<VectorMatch>,
+}
+
+pub async fn query_vectors(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(collection): Path<String>,
+    Json(request): Json<QueryVectorsRequest>,
+) -> Result<Json<QueryVectorsResponse>, AIError> {
+    state.budget.ensure_within_budget(&tenant_context.tenant_id).await?;
+    
+    let provider = state.router.route_provider(&tenant_context.tenant_id, request.provider, "embedding").await?;
+    let requested_at = Utc::now();
+    let result = state.embedding_service.query(
+        &collection,
+        request.query,
+        request.top_k.unwrap_or(10),
+        request.filter.as_ref(),
+        request.model,
+        provider.as_ref(),
+        request_context(&tenant_context),
+    ).await?;
+    record_usage(&state.usage_tracker, request_context(&tenant_context), &result.model, AICapability::TextEmbedding, &result.usage, requested_at).await;
+    
+    Ok(Json(QueryVectorsResponse { matches: result.matches }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DeleteVectorsRequest {
+    pub ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct DeleteVectorsResponse {
+    pub deleted: u64,
+}
+
+pub async fn delete_vectors(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(collection): Path<String>,
+    Json(request): Json<DeleteVectorsRequest>,
+) -> Result<Json<DeleteVectorsResponse>, AIError> {
+    let deleted = state.embedding_service
+        .delete(&tenant_context.tenant_id, &collection, &request.ids)
+        .await?;
+    
+    Ok(Json(DeleteVectorsResponse { deleted }))
+}
+
+// Audio endpoints. Uploads up to `audio.max_upload_bytes` are transcribed
+// in the request; longer recordings are stored in file-service and
+// transcribed by a job.
+pub async fn transcribe_audio(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    mut multipart: Multipart,
+) -> Result<Json<TranscriptionResult>, AIError> {
+    let mut audio = None;
+    let mut language = None;
+    let mut prompt = None;
+    let mut model = None;
+    let mut provider = None;
+    
+    while let Some(field) = multipart.next_field().await
+        .map_err(|e| AIError::BadRequest(format!("Invalid multipart data: {}", e)))?
+    {
+        let name = field.name().unwrap_or_default().to_string();
+        if name == "file" {
+            let filename = field.file_name().unwrap_or("audio").to_string();
+            let bytes = field.bytes().await
+                .map_err(|e| AIError::BadRequest(format!("Failed to read audio: {}", e)))?;
+            audio = Some((filename, bytes.to_vec()));
+            continue;
+        }
+        
+        let value = field.text().await
+            .map_err(|e| AIError::BadRequest(format!("Invalid {} field: {}", name, e)))?;
+        match name.as_str() {
+            "language" => language = Some(value),
+            "prompt" => prompt = Some(value),
+            "model" => model = Some(value),
+            "provider" => provider = Some(
+                serde_json::from_value::<AIProvider>(serde_json::Value::String(value))
+                    .map_err(|_| AIError::BadRequest("Invalid provider".to_string()))?,
+            ),
+            _ => {}
+        }
+    }
+    
+    let (filename, audio) = audio.ok_or_else(|| AIError::Validation("file is required".to_string()))?;
+    if audio.len() > state.audio.max_upload_bytes() {
+        return Err(AIError::Validation(format!(
+            "Audio over {} bytes must be transcribed with a transcription job",
+            state.audio.max_upload_bytes()
+        )));
+    }
+    
+    state.budget.ensure_within_budget(&tenant_context.tenant_id).await?;
+    let provider = state.router.route_provider(&tenant_context.tenant_id, provider, "audio").await?;
+    let requested_at = Utc::now();
+    let result = state.audio.transcribe(
+        TranscriptionRequest {
+            audio,
+            filename,
+            language,
+            prompt,
+            model,
+            context: request_context(&tenant_context),
+        },
+        provider.as_ref(),
+    ).await?;
+    record_usage(&state.usage_tracker, request_context(&tenant_context), &result.model, AICapability::SpeechToText, &result.usage, requested_at).await;
+    
+    Ok(Json(result))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SynthesizeSpeechRequest {
+    pub text: String,
+    pub voice: Option<String>,
+    #[serde(default)]
+    pub format: AudioFormat,
+    pub speed: Option<f32>,
+    pub model: Option<String>,
+    pub provider: Option<AIProvider>,
+}
+
+pub async fn synthesize_speech(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(request): Json<SynthesizeSpeechRequest>,
+) -> Result<Response, AIError> {
+    state.budget.ensure_within_budget(&tenant_context.tenant_id).await?;
+    
+    let provider = state.router.route_provider(&tenant_context.tenant_id, request.provider, "audio").await?;
+    let requested_at = Utc::now();
+    let speech = state.audio.synthesize(
+        SpeechSynthesisRequest {
+            text: request.text,
+            voice: request.voice,
+            format: request.format,
+            speed: request.speed,
+            model: request.model,
+            context: request_context(&tenant_context),
+        },
+        provider.as_ref(),
+    ).await?;
+    record_usage(&state.usage_tracker, request_context(&tenant_context), &speech.model, AICapability::TextToSpeech, &speech.usage, requested_at).await;
+    
+    Ok(([(header::CONTENT_TYPE, speech.format.content_type())], speech.audio).into_response())
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTranscriptionJobRequest {
+    pub file_id: Uuid,
+    /// Vector collection the transcript is indexed into
+    pub collection: Option<String>,
+    pub language: Option<String>,
+    pub model: Option<String>,
+    pub provider: Option<AIProvider>,
+}
+
+// Start a transcription job. The job runs in the background; its
+// transcript is searchable in the collection once it completes.
+pub async fn create_transcription_job(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(request): Json<CreateTranscriptionJobRequest>,
+) -> Result<(StatusCode, Json<TranscriptionJob>), AIError> {
+    state.budget.ensure_within_budget(&tenant_context.tenant_id).await?;
+    
+    let provider = state.router.route_provider(&tenant_context.tenant_id, request.provider, "audio").await?;
+    let transcribe = TranscribeFileRequest {
+        job_id: Uuid::new_v4(),
+        file_id: request.file_id,
+        collection: request.collection,
+        language: request.language,
+        model: request.model,
+        provider,
+        context: request_context(&tenant_context),
+    };
+    let job = state.audio.create_job(&transcribe).await?;
+    
+    let audio = state.audio.clone();
+    let usage_tracker = state.usage_tracker.clone();
+    tokio::spawn(async move {
+        let requested_at = Utc::now();
+        match audio.run_job(&transcribe).await {
+            Ok(transcribed) => {
+                if let Some(transcription) = &transcribed.transcription {
+                    record_usage(&usage_tracker, transcribe.context.clone(), &transcription.model, AICapability::SpeechToText, &transcription.usage, requested_at).await;
+                }
+                if let Some(index) = &transcribed.index {
+                    record_usage(&usage_tracker, transcribe.context.clone(), &index.model, AICapability::TextEmbedding, &index.usage, requested_at).await;
+                }
+            }
+            Err(e) => tracing::warn!("Transcription job {} failed: {}", transcribe.job_id, e),
+        }
+    });
+    
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+pub async fn get_transcription_job(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(job_id): Path<Uuid>,
+) -> Result<Json<TranscriptionJob>, AIError> {
+    let job = state.audio.get_job(&tenant_context.tenant_id, job_id).await?;
+    Ok(Json(job))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateBatchRequest {
+    pub source: BatchSource,
+    pub operations: BatchOperations,
+    pub model: Option<String>,
+}
+
+// Start a batch over file-service documents. The batch runs in the
+// background; its CSV report is stored in file-service once it completes.
+pub async fn create_batch(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(request): Json<CreateBatchRequest>,
+) -> Result<(StatusCode, Json<BatchJob>), AIError> {
+    state.budget.ensure_within_budget(&tenant_context.tenant_id).await?;
+    
+    // Batches are served by one model, chosen for their first operation
+    let capability = if request.operations.classify.is_some() {
+        AICapability::TextClassification
+    } else {
+        AICapability::TextSummarization
+    };
+    let model = resolve_model(&state, &tenant_context, request.model.clone(), &capability).await?;
+    let context = request_context(&tenant_context);
+    let prepare = PrepareBatchRequest {
+        job_id: Uuid::new_v4(),
+        source: request.source,
+        operations: request.operations,
+        model: request.model,
+        context: context.clone(),
+    };
+    let plan = state.batches.prepare(&prepare, &model).await?;
+    let job = plan.job.clone();
+    
+    let batches = state.batches.clone();
+    let usage_tracker = state.usage_tracker.clone();
+    tokio::spawn(async move {
+        let process = |file_id| {
+            let request = ProcessBatchItemRequest {
+                job_id: prepare.job_id,
+                file_id,
+                context: context.clone(),
+            };
+            let batches = &batches;
+            let usage_tracker = &usage_tracker;
+            async move {
+                let requested_at = Utc::now();
+                let processed = batches.process_item(&request).await?;
+                for (capability, usage) in &processed.usage {
+                    record_usage(usage_tracker, request.context.clone(), &processed.model, capability.clone(), usage, requested_at).await;
+                }
+                Ok(processed)
+            }
+        };
+        if let Err(e) = batches.run(&context, plan, process).await {
+            tracing::warn!("Batch {} failed: {}", prepare.job_id, e);
+        }
+    });
+    
+    Ok((StatusCode::ACCEPTED, Json(job)))
+}
+
+pub async fn get_batch(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(batch_id): Path<Uuid>,
+) -> Result<Json<BatchJob>, AIError> {
+    let job = state.batches.get_job(&tenant_context.tenant_id, batch_id).await?;
+    Ok(Json(job))
+}
+
+pub async fn get_batch_items(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(batch_id): Path<Uuid>,
+) -> Result<Json<Vec<BatchItem>>, AIError> {
+    let items = state.batches.list_items(&tenant_context.tenant_id, batch_id).await?;
+    Ok(Json(items))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct GenerateImageRequest {
+    pub prompt: String,
+    pub size: Option<String>,
+    pub quality: Option<String>,
+    pub count: Option<u32>,
+    pub model: Option<String>,
+    pub provider: Option<AIProvider>,
+}
+
+// Generate images. The images and their previews are stored in
+// file-service as the requesting user.
+pub async fn generate_image(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(request): Json<GenerateImageRequest>,
+) -> Result<Json<ImageGenerationResult>, AIError> {
+    state.budget.ensure_within_budget(&tenant_context.tenant_id).await?;
+    
+    let provider = state.router.route_provider(&tenant_context.tenant_id, request.provider, "image").await?;
+    let requested_at = Utc::now();
+    let result = state.images.generate(
+        ImageGenerationRequest {
+            prompt: request.prompt,
+            size: request.size,
+            quality: request.quality,
+            count: request.count,
+            model: request.model,
+            context: request_context(&tenant_context),
+        },
+        provider.as_ref(),
+    ).await?;
+    record_usage(&state.usage_tracker, request_context(&tenant_context), &result.model, AICapability::ImageGeneration, &result.usage, requested_at).await;
+    
+    Ok(Json(result))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct AnalyzeImageRequest {
+    /// file-service images the prompt is about
+    pub file_ids: Vec<Uuid>,
+    pub prompt: String,
+    pub max_tokens: Option<u32>,
+    pub model: Option<String>,
+    pub provider: Option<AIProvider>,
+}
+
+// Analyze images stored in file-service
+pub async fn analyze_image(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(request): Json<AnalyzeImageRequest>,
+) -> Result<Json<ImageAnalysisResult>, AIError> {
+    state.budget.ensure_within_budget(&tenant_context.tenant_id).await?;
+    
+    let provider = state.router.route_provider(&tenant_context.tenant_id, request.provider, "image").await?;
+    let requested_at = Utc::now();
+    let result = state.images.analyze(
+        ImageAnalysisRequest {
+            file_ids: request.file_ids,
+            prompt: request.prompt,
+            max_tokens: request.max_tokens,
+            model: request.model,
+            context: request_context(&tenant_context),
+        },
+        provider.as_ref(),
+    ).await?;
+    record_usage(&state.usage_tracker, request_context(&tenant_context), &result.model, AICapability::ImageAnalysis, &result.usage, requested_at).await;
+    
+    Ok(Json(result))
+}
+
+fn request_context(tenant_context: &TenantContext) -> RequestContext {
+    RequestContext {
+        tenant_id: tenant_context.tenant_id.clone(),
+        user_id: tenant_context.user_id.clone(),
+        session_id: None,
+        workflow_id: None,
+        activity_id: None,
+    }
+}
+
+// The prompt, model and parameters of a generation request. A template's
+// model and parameters apply unless the request sets its own.
+async fn resolve_prompt(
+    state: &AppState,
+    tenant_context: &TenantContext,
+    request: GenerateTextRequest,
+) -> AIResult<(String, Option<String>, AIParameters)> {
+    match (request.prompt, request.template) {
+        (None, Some(template)) => {
+            let rendered = state.prompt_templates
+                .render(&tenant_context.tenant_id, &template.reference, &template.variables)
+                .await?;
+            Ok((
+                rendered.prompt,
+                request.model.or(rendered.model),
+                request.parameters.unwrap_or(rendered.parameters),
+            ))
+        }
+        (Some(prompt), None) => Ok((prompt, request.model, request.parameters.unwrap_or_default())),
+        _ => Err(AIError::Validation("Exactly one of prompt and template is required".to_string())),
+    }
+}
+
+// The model to serve a request with: the requested model, or the default,
+// after applying the tenant's monthly budget
+async fn resolve_model(
+    state: &AppState,
+    tenant_context: &TenantContext,
+    requested: Option<String>,
+    capability: &AICapability,
+) -> AIResult<String> {
+    let model = requested.unwrap_or_else(|| "gpt-3.5-turbo".to_string());
+    let model = state.budget.resolve_model(&tenant_context.tenant_id, &model, capability).await?;
+    state.router.route(&tenant_context.tenant_id, &model, capability).await
+}
+
+// Meter a served request. The response is not failed when recording fails.
+async fn record_usage(
+    usage_tracker: &UsageTracker,
+    context: RequestContext,
+    model: &str,
+    capability: AICapability,
+    usage: &TokenUsage,
+    requested_at: DateTime<Utc>,
+) {
+    let usage_record = AIUsageRecord {
+        id: Uuid::new_v4(),
+        tenant_id: context.tenant_id,
+        user_id: context.user_id,
+        workflow_id: context.workflow_id,
+        activity_id: context.activity_id,
+        model: model.to_string(),
+        capability,
+        usage: usage.clone(),
+        request_timestamp: requested_at,
+        response_timestamp: Utc::now(),
+        success: true,
+        error_code: None,
+    };
+    
+    if let Err(e) = usage_tracker.record_usage(usage_record).await {
+        tracing::warn!("Failed to record AI usage: {}", e);
+    }
+}
+
+// The text to send to a model, after the tenant's moderation policy
+async fn moderate_input(state: &AppState, tenant_context: &TenantContext, text: &str) -> AIResult<String> {
+    let moderated = state.moderator
+        .moderate(&request_context(tenant_context), ModerationStage::Input, text)
+        .await?;
+    Ok(moderated.text)
+}
+
+// Serve a completion from the tenant's response cache, or produce and cache
+// it. Hits are not metered, since no provider serves them; embedding the
+// prompt for similarity matching is. `X-Cache` tells the client which it got.
+// Either way, the interaction goes to the tenant's audit log.
+async fn cached_response<T, F>(
+    state: &AppState,
+    headers: &HeaderMap,
+    request: CacheRequest<'_>,
+    produce: F,
+) -> AIResult<Response>
+where
+    T: Serialize,
+    F: Future<Output = AIResult<T>>,
+{
+    let context = request.context.clone();
+    let mut audit = AuditEntry {
+        context: context.clone(),
+        capability: request.capability.clone(),
+        model: request.model.to_string(),
+        prompt: request.prompt.to_string(),
+        response: None,
+        outcome: AuditOutcome::Success,
+        error: None,
+        usage: None,
+        cached: false,
+    };
+    let requested_at = Utc::now();
+    let mut lookup = state.response_cache.lookup(request, cache_mode(headers)).await;
+    if let Some((model, usage)) = &lookup.embedding_usage {
+        record_usage(&state.usage_tracker, context, model, AICapability::TextEmbedding, usage, requested_at).await;
+    }
+
+    let (response, cache_status) = match lookup.response.take() {
+        Some(response) => {
+            audit.cached = true;
+            (response, "hit")
+        }
+        None => {
+            let produced = match produce.await {
+                Ok(produced) => serde_json::to_value(produced)?,
+                Err(e) => {
+                    audit.outcome = match e {
+                        AIError::ContentFiltered(_) => AuditOutcome::Blocked,
+                        _ => AuditOutcome::Failed,
+                    };
+                    audit.error = Some(e.to_string());
+                    state.audit.record(audit).await;
+                    return Err(e);
+                }
+            };
+            audit.usage = produced.get("usage").and_then(|usage| serde_json::from_value(usage.clone()).ok());
+            state.response_cache.store(lookup, &produced).await;
+            (produced, "miss")
+        }
+    };
+    audit.response = Some(response.to_string());
+    state.audit.record(audit).await;
+    Ok(([("x-cache", cache_status)], Json(response)).into_response())
+}
+
+// `Cache-Control: no-cache` skips cached responses, and `no-store` also keeps
+// the response out of the cache
+fn cache_mode(headers: &HeaderMap) -> CacheMode {
+    let directives = headers
+        .get(header::CACHE_CONTROL)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let has = |directive: &str| directives.split(',').any(|d| d.trim().eq_ignore_ascii_case(directive));
+
+    if has("no-store") {
+        CacheMode::Bypass
+    } else if has("no-cache") {
+        CacheMode::Refresh
+    } else {
+        CacheMode::Use
+    }
+}
+
+// Streamed output has reached the client before it can be moderated, so it
+// is checked afterwards only to report findings to security-service.
+fn flag_streamed_output(moderator: Arc<Moderator>, context: RequestContext, output: String) {
+    tokio::spawn(async move {
+        if let Err(e) = moderator.moderate(&context, ModerationStage::Output, &output).await {
+            tracing::warn!("Streamed output for tenant {} failed moderation: {}", context.tenant_id, e);
+        }
+    });
 }
 
 // Classify text endpoint
@@ -138,21 +963,22 @@ pub struct ClassifyTextResponse {
 pub async fn classify_text(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
+    headers: HeaderMap,
     Json(request): Json<ClassifyTextRequest>,
-) -> Result<Json<ClassifyTextResponse>, AIError> {
+) -> Result<Response, AIError> {
     // This would normally use the activities through a workflow
     // For direct endpoint, we'll create a simplified version
     let model_registry = state.ai_service.get_model_registry();
     let provider_manager = state.ai_service.get_provider_manager();
     
-    let model = request.model.unwrap_or_else(|| "gpt-3.5-turbo".to_string());
+    let model = resolve_model(&state, &tenant_context, request.model, &AICapability::TextClassification).await?;
     let model_info = model_registry.get_model(&model)
         .ok_or_else(|| AIError::ModelNotAvailable(format!("Model {} not found", model)))?;
     
     let provider = provider_manager.get_provider(&model_info.provider)?;
     
     let classification_request = TextClassificationRequest {
-        text: request.text,
+        text: moderate_input(&state, &tenant_context, &request.text).await?,
         categories: request.categories,
         model: Some(model),
         context: RequestContext {
@@ -163,16 +989,27 @@ pub async fn classify_text(
             activity_id: None,
         },
     };
+    let cache_request = CacheRequest {
+        context: &classification_request.context,
+        capability: AICapability::TextClassification,
+        model: &model_info.id,
+        prompt: &classification_request.text,
+        parameters: json!({ "categories": classification_request.categories }),
+    };
     
-    let result = provider.classify_text(&classification_request).await
-        .map_err(|e| AIError::AIProvider(e.to_string()))?;
-    
-    Ok(Json(ClassifyTextResponse {
-        category: result.category,
-        confidence: result.confidence,
-        all_scores: result.all_scores,
-        usage: result.usage,
-    }))
+    cached_response(&state, &headers, cache_request, async {
+        let requested_at = Utc::now();
+        let result = provider.classify_text(&classification_request).await
+            .map_err(|e| AIError::AIProvider(e.to_string()))?;
+        record_usage(&state.usage_tracker, classification_request.context.clone(), &model_info.id, AICapability::TextClassification, &result.usage, requested_at).await;
+        
+        Ok(ClassifyTextResponse {
+            category: result.category,
+            confidence: result.confidence,
+            all_scores: result.all_scores,
+            usage: result.usage,
+        })
+    }).await
 }
 
 // Summarize text endpoint
@@ -195,19 +1032,20 @@ pub struct SummarizeTextResponse {
 pub async fn summarize_text(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
+    headers: HeaderMap,
     Json(request): Json<SummarizeTextRequest>,
-) -> Result<Json<SummarizeTextResponse>, AIError> {
+) -> Result<Response, AIError> {
     let model_registry = state.ai_service.get_model_registry();
     let provider_manager = state.ai_service.get_provider_manager();
     
-    let model = request.model.unwrap_or_else(|| "gpt-3.5-turbo".to_string());
+    let model = resolve_model(&state, &tenant_context, request.model, &AICapability::TextSummarization).await?;
     let model_info = model_registry.get_model(&model)
         .ok_or_else(|| AIError::ModelNotAvailable(format!("Model {} not found", model)))?;
     
     let provider = provider_manager.get_provider(&model_info.provider)?;
     
     let summarization_request = TextSummarizationRequest {
-        text: request.text,
+        text: moderate_input(&state, &tenant_context, &request.text).await?,
         max_length: request.max_length,
         style: request.style,
         model: Some(model),
@@ -219,16 +1057,35 @@ pub async fn summarize_text(
             activity_id: None,
         },
     };
+    let cache_request = CacheRequest {
+        context: &summarization_request.context,
+        capability: AICapability::TextSummarization,
+        model: &model_info.id,
+        prompt: &summarization_request.text,
+        parameters: json!({
+            "max_length": summarization_request.max_length,
+            "style": summarization_request.style,
+        }),
+    };
     
-    let result = provider.summarize_text(&summarization_request).await
-        .map_err(|e| AIError::AIProvider(e.to_string()))?;
-    
-    Ok(Json(SummarizeTextResponse {
-        summary: result.summary,
-        key_points: result.key_points,
-        compression_ratio: result.compression_ratio,
-        usage: result.usage,
-    }))
+    cached_response(&state, &headers, cache_request, async {
+        let requested_at = Utc::now();
+        let result = provider.summarize_text(&summarization_request).await
+            .map_err(|e| AIError::AIProvider(e.to_string()))?;
+        record_usage(&state.usage_tracker, summarization_request.context.clone(), &model_info.id, AICapability::TextSummarization, &result.usage, requested_at).await;
+        
+        let summary = state.moderator
+            .moderate(&summarization_request.context, ModerationStage::Output, &result.summary)
+            .await?
+            .text;
+        
+        Ok(SummarizeTextResponse {
+            summary,
+            key_points: result.key_points,
+            compression_ratio: result.compression_ratio,
+            usage: result.usage,
+        })
+    }).await
 }
 
 // Extract entities endpoint
@@ -248,19 +1105,20 @@ pub struct ExtractEntitiesResponse {
 pub async fn extract_entities(
     State(state): State<AppState>,
     Extension(tenant_context): Extension<TenantContext>,
+    headers: HeaderMap,
     Json(request): Json<ExtractEntitiesRequest>,
-) -> Result<Json<ExtractEntitiesResponse>, AIError> {
+) -> Result<Response, AIError> {
     let model_registry = state.ai_service.get_model_registry();
     let provider_manager = state.ai_service.get_provider_manager();
     
-    let model = request.model.unwrap_or_else(|| "gpt-3.5-turbo".to_string());
+    let model = resolve_model(&state, &tenant_context, request.model, &AICapability::EntityExtraction).await?;
     let model_info = model_registry.get_model(&model)
         .ok_or_else(|| AIError::ModelNotAvailable(format!("Model {} not found", model)))?;
     
     let provider = provider_manager.get_provider(&model_info.provider)?;
     
     let extraction_request = EntityExtractionRequest {
-        text: request.text,
+        text: moderate_input(&state, &tenant_context, &request.text).await?,
         entity_types: request.entity_types,
         model: Some(model),
         context: RequestContext {
@@ -271,16 +1129,434 @@ pub async fn extract_entities(
             activity_id: None,
         },
     };
+    let cache_request = CacheRequest {
+        context: &extraction_request.context,
+        capability: AICapability::EntityExtraction,
+        model: &model_info.id,
+        prompt: &extraction_request.text,
+        parameters: json!({ "entity_types": extraction_request.entity_types }),
+    };
     
-    let result = provider.extract_entities(&extraction_request).await
-        .map_err(|e| AIError::AIProvider(e.to_string()))?;
+    cached_response(&state, &headers, cache_request, async {
+        let requested_at = Utc::now();
+        let result = provider.extract_entities(&extraction_request).await
+            .map_err(|e| AIError::AIProvider(e.to_string()))?;
+        record_usage(&state.usage_tracker, extraction_request.context.clone(), &model_info.id, AICapability::EntityExtraction, &result.usage, requested_at).await;
+        
+        Ok(ExtractEntitiesResponse {
+            entities: result.entities,
+            usage: result.usage,
+        })
+    }).await
+}
+
+// Prompt template endpoints. Templates are versioned per tenant; built-in
+// templates are listed alongside the tenant's own until it overrides them.
+#[derive(Debug, Deserialize)]
+pub struct CreatePromptTemplateRequest {
+    pub name: String,
+    #[serde(flatten)]
+    pub template: PromptTemplateInput,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RenderPromptTemplateRequest {
+    pub version: Option<i32>,
+    #[serde(default)]
+    pub variables: HashMap<String, String>,
+}
+
+pub async fn list_prompt_templates(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<Json<Vec<PromptTemplate>>, AIError> {
+    let templates = state.prompt_templates.list(&tenant_context.tenant_id).await?;
+    Ok(Json(templates))
+}
+
+pub async fn create_prompt_template(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(request): Json<CreatePromptTemplateRequest>,
+) -> Result<(StatusCode, Json<PromptTemplate>), AIError> {
+    let template = state.prompt_templates
+        .create_version(&request_context(&tenant_context), &request.name, request.template)
+        .await?;
+    Ok((StatusCode::CREATED, Json(template)))
+}
+
+pub async fn create_prompt_template_version(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(name): Path<String>,
+    Json(request): Json<PromptTemplateInput>,
+) -> Result<(StatusCode, Json<PromptTemplate>), AIError> {
+    let template = state.prompt_templates
+        .create_version(&request_context(&tenant_context), &name, request)
+        .await?;
+    Ok((StatusCode::CREATED, Json(template)))
+}
+
+pub async fn get_prompt_template_versions(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(name): Path<String>,
+) -> Result<Json<Vec<PromptTemplate>>, AIError> {
+    let versions = state.prompt_templates.versions(&tenant_context.tenant_id, &name).await?;
+    Ok(Json(versions))
+}
+
+pub async fn get_prompt_template_version(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path((name, version)): Path<(String, i32)>,
+) -> Result<Json<PromptTemplate>, AIError> {
+    let template = state.prompt_templates
+        .get(&tenant_context.tenant_id, &PromptReference { name, version: Some(version) })
+        .await?;
+    Ok(Json(template))
+}
+
+pub async fn delete_prompt_template(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, AIError> {
+    state.prompt_templates.delete(&tenant_context.tenant_id, &name, None).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn delete_prompt_template_version(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path((name, version)): Path<(String, i32)>,
+) -> Result<StatusCode, AIError> {
+    state.prompt_templates.delete(&tenant_context.tenant_id, &name, Some(version)).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn render_prompt_template(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(name): Path<String>,
+    Json(request): Json<RenderPromptTemplateRequest>,
+) -> Result<Json<RenderedPrompt>, AIError> {
+    let rendered = state.prompt_templates
+        .render(
+            &tenant_context.tenant_id,
+            &PromptReference { name, version: request.version },
+            &request.variables,
+        )
+        .await?;
+    Ok(Json(rendered))
+}
+
+// Moderation policy endpoints. Without a policy of its own a tenant is
+// moderated with the service defaults.
+pub async fn get_moderation_policy(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<Json<ModerationPolicy>, AIError> {
+    let policy = state.moderator.policies().get(&tenant_context.tenant_id).await?;
+    Ok(Json(policy))
+}
+
+pub async fn put_moderation_policy(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(policy): Json<ModerationPolicy>,
+) -> Result<Json<ModerationPolicy>, AIError> {
+    state.moderator.policies().put(&tenant_context.tenant_id, &policy).await?;
+    invalidate_moderated_responses(&state, &tenant_context).await;
+    Ok(Json(policy))
+}
+
+pub async fn delete_moderation_policy(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<StatusCode, AIError> {
+    state.moderator.policies().delete(&tenant_context.tenant_id).await?;
+    invalidate_moderated_responses(&state, &tenant_context).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Cached responses were moderated under the old policy
+async fn invalidate_moderated_responses(state: &AppState, tenant_context: &TenantContext) {
+    let invalidation = CacheInvalidation::default();
+    if let Err(e) = state.response_cache.invalidate(&tenant_context.tenant_id, &invalidation).await {
+        tracing::warn!("Failed to invalidate cached responses of tenant {}: {}", tenant_context.tenant_id, e);
+    }
+}
+
+// Response cache endpoints. Without a policy of its own a tenant is cached
+// with the service defaults.
+pub async fn get_cache_policy(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<Json<CachePolicy>, AIError> {
+    let policy = state.response_cache.policy(&tenant_context.tenant_id).await?;
+    Ok(Json(policy))
+}
+
+pub async fn put_cache_policy(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(policy): Json<CachePolicy>,
+) -> Result<Json<CachePolicy>, AIError> {
+    state.response_cache.put_policy(&tenant_context.tenant_id, &policy).await?;
+    Ok(Json(policy))
+}
+
+pub async fn delete_cache_policy(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<StatusCode, AIError> {
+    state.response_cache.delete_policy(&tenant_context.tenant_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Serialize)]
+pub struct InvalidateCacheResponse {
+    pub invalidated: u64,
+}
+
+// Drop cached responses, optionally only those of a model or capability
+pub async fn invalidate_cache(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(invalidation): Json<CacheInvalidation>,
+) -> Result<Json<InvalidateCacheResponse>, AIError> {
+    let invalidated = state.response_cache.invalidate(&tenant_context.tenant_id, &invalidation).await?;
+    Ok(Json(InvalidateCacheResponse { invalidated }))
+}
+
+pub async fn get_cache_stats(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<Json<CacheStats>, AIError> {
+    let stats = state.response_cache.stats(&tenant_context.tenant_id).await?;
+    Ok(Json(stats))
+}
+
+// Audit log endpoints. Records hold what the tenant's audit policy keeps of
+// each interaction.
+pub async fn list_audit_records(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Query(query): Query<AuditQuery>,
+) -> Result<Json<Vec<AuditRecord>>, AIError> {
+    let records = state.audit.list(&tenant_context.tenant_id, &query).await?;
+    Ok(Json(records))
+}
+
+pub async fn get_audit_record(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(record_id): Path<Uuid>,
+) -> Result<Json<AuditRecord>, AIError> {
+    let record = state.audit.get(&tenant_context.tenant_id, record_id).await?;
+    Ok(Json(record))
+}
+
+pub async fn get_audit_policy(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<Json<AuditPolicy>, AIError> {
+    let policy = state.audit.policy(&tenant_context.tenant_id).await?;
+    Ok(Json(policy))
+}
+
+pub async fn put_audit_policy(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(policy): Json<AuditPolicy>,
+) -> Result<Json<AuditPolicy>, AIError> {
+    state.audit.put_policy(&tenant_context.tenant_id, &policy).await?;
+    Ok(Json(policy))
+}
+
+pub async fn delete_audit_policy(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<StatusCode, AIError> {
+    state.audit.delete_policy(&tenant_context.tenant_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct ExportAuditRequest {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ExportAuditResponse {
+    pub exported: u64,
+}
+
+// Send records not yet exported to security-service. Large backlogs go in
+// batches, so callers repeat until nothing is exported.
+pub async fn export_audit_records(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(request): Json<ExportAuditRequest>,
+) -> Result<Json<ExportAuditResponse>, AIError> {
+    let exported = state.audit.export(&tenant_context.tenant_id, request.from, request.to).await?;
+    Ok(Json(ExportAuditResponse { exported }))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PiiTextRequest {
+    pub text: String,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PiiTextResponse {
+    pub text: String,
+}
+
+// PII endpoints. They apply the tenant's PII policy whether or not it is
+// enabled for prompts, so other services can clear documents and events
+// with it.
+pub async fn detect_pii(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(request): Json<PiiTextRequest>,
+) -> Result<Json<Vec<PiiDetection>>, AIError> {
+    let detections = state.moderator.pii()
+        .detect(&request_context(&tenant_context), &request.text)
+        .await?;
+    Ok(Json(detections))
+}
+
+pub async fn redact_pii(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(request): Json<PiiTextRequest>,
+) -> Result<Json<RedactedText>, AIError> {
+    let redacted = state.moderator.pii()
+        .redact(&request_context(&tenant_context), &request.text)
+        .await?;
+    Ok(Json(redacted))
+}
+
+// Give tokenized values back, e.g. in text generated from a redacted prompt
+pub async fn restore_pii(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(request): Json<PiiTextRequest>,
+) -> Result<Json<PiiTextResponse>, AIError> {
+    let text = state.moderator.pii().restore(&tenant_context.tenant_id, &request.text).await?;
+    Ok(Json(PiiTextResponse { text }))
+}
+
+pub async fn get_pii_policy(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<Json<PiiPolicy>, AIError> {
+    let policy = state.moderator.pii().policy(&tenant_context.tenant_id).await?;
+    Ok(Json(policy))
+}
+
+pub async fn put_pii_policy(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(policy): Json<PiiPolicy>,
+) -> Result<Json<PiiPolicy>, AIError> {
+    state.moderator.pii().put_policy(&tenant_context.tenant_id, &policy).await?;
+    invalidate_moderated_responses(&state, &tenant_context).await;
+    Ok(Json(policy))
+}
+
+pub async fn delete_pii_policy(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<StatusCode, AIError> {
+    state.moderator.pii().delete_policy(&tenant_context.tenant_id).await?;
+    invalidate_moderated_responses(&state, &tenant_context).await;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Tenant provider routing policy endpoints
+pub async fn get_routing_policy(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<Json<ProviderRoutingPolicy>, AIError> {
+    let policy = state.router.policy(&tenant_context.tenant_id).await?;
+    Ok(Json(policy))
+}
+
+pub async fn put_routing_policy(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(policy): Json<ProviderRoutingPolicy>,
+) -> Result<Json<ProviderRoutingPolicy>, AIError> {
+    state.router.put_policy(&tenant_context.tenant_id, &policy).await?;
+    Ok(Json(policy))
+}
+
+pub async fn delete_routing_policy(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<StatusCode, AIError> {
+    state.router.delete_policy(&tenant_context.tenant_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Local model management endpoints. Models on the Ollama server are shared
+// by all tenants.
+#[derive(Debug, Serialize)]
+pub struct LocalModelsResponse {
+    pub installed: Vec<LocalModel>,
+    pub loaded: Vec<LoadedModel>,
+}
+
+pub async fn list_local_models(
+    State(state): State<AppState>,
+) -> Result<Json<LocalModelsResponse>, AIError> {
+    let provider_manager = state.ai_service.get_provider_manager();
+    let local = provider_manager.local()?;
     
-    Ok(Json(ExtractEntitiesResponse {
-        entities: result.entities,
-        usage: result.usage,
+    Ok(Json(LocalModelsResponse {
+        installed: local.list_models().await?,
+        loaded: local.loaded_models().await?,
     }))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct PullLocalModelRequest {
+    pub model: String,
+}
+
+pub async fn pull_local_model(
+    State(state): State<AppState>,
+    Json(request): Json<PullLocalModelRequest>,
+) -> Result<(StatusCode, Json<ModelPull>), AIError> {
+    if request.model.trim().is_empty() {
+        return Err(AIError::Validation("model is required".to_string()));
+    }
+    
+    let provider_manager = state.ai_service.get_provider_manager();
+    let pull = provider_manager.local()?.pull_model(request.model.trim());
+    Ok((StatusCode::ACCEPTED, Json(pull)))
+}
+
+pub async fn list_local_model_pulls(
+    State(state): State<AppState>,
+) -> Result<Json<Vec<ModelPull>>, AIError> {
+    let provider_manager = state.ai_service.get_provider_manager();
+    Ok(Json(provider_manager.local()?.pulls()))
+}
+
+pub async fn delete_local_model(
+    State(state): State<AppState>,
+    Path(model): Path<String>,
+) -> Result<StatusCode, AIError> {
+    let provider_manager = state.ai_service.get_provider_manager();
+    provider_manager.local()?.delete_model(&model).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
 // Usage statistics endpoint
 #[derive(Debug, Deserialize)]
 pub struct UsageStatsQuery {
@@ -323,6 +1599,51 @@ pub async fn get_cost_breakdown(
     Ok(Json(cost_breakdown))
 }
 
+// Usage per user endpoint
+#[derive(Debug, Deserialize)]
+pub struct UserUsageQuery {
+    pub period_start: Option<DateTime<Utc>>,
+    pub period_end: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct UserUsage {
+    pub user_id: String,
+    pub requests: u64,
+    pub cost: f64,
+}
+
+pub async fn get_usage_by_user(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Query(query): Query<UserUsageQuery>,
+) -> Result<Json<Vec<UserUsage>>, AIError> {
+    let period_end = query.period_end.unwrap_or_else(Utc::now);
+    let period_start = query.period_start.unwrap_or_else(|| period_end - chrono::Duration::days(30));
+    
+    let users = state.usage_tracker.get_top_users_by_usage(
+        &tenant_context.tenant_id,
+        period_start,
+        period_end,
+        query.limit.unwrap_or(50).clamp(1, 500),
+    ).await?;
+    
+    Ok(Json(users
+        .into_iter()
+        .map(|(user_id, requests, cost)| UserUsage { user_id, requests, cost })
+        .collect()))
+}
+
+// Monthly budget endpoint
+pub async fn get_budget_status(
+    State(state): State<AppState>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> Result<Json<BudgetStatus>, AIError> {
+    let status = state.budget.status(&tenant_context.tenant_id).await?;
+    Ok(Json(status))
+}
+
 // Provider health endpoint
 pub async fn get_provider_health(
     State(state): State<AppState>,
@@ -332,6 +1653,9 @@ pub async fn get_provider_health(
         "openai" => AIProvider::OpenAI,
         "anthropic" => AIProvider::Anthropic,
         "local" => AIProvider::Local,
+        "azure_openai" => AIProvider::AzureOpenAI,
+        "bedrock" => AIProvider::Bedrock,
+        "gemini" => AIProvider::Gemini,
         _ => return Err(AIError::BadRequest("Invalid provider".to_string())),
     };
     
@@ -359,6 +1683,9 @@ pub async fn get_health_history(
         "openai" => AIProvider::OpenAI,
         "anthropic" => AIProvider::Anthropic,
         "local" => AIProvider::Local,
+        "azure_openai" => AIProvider::AzureOpenAI,
+        "bedrock" => AIProvider::Bedrock,
+        "gemini" => AIProvider::Gemini,
         _ => return Err(AIError::BadRequest("Invalid provider".to_string())),
     };
     
@@ -378,6 +1705,9 @@ pub async fn get_availability_metrics(
         "openai" => AIProvider::OpenAI,
         "anthropic" => AIProvider::Anthropic,
         "local" => AIProvider::Local,
+        "azure_openai" => AIProvider::AzureOpenAI,
+        "bedrock" => AIProvider::Bedrock,
+        "gemini" => AIProvider::Gemini,
         _ => return Err(AIError::BadRequest("Invalid provider".to_string())),
     };
     
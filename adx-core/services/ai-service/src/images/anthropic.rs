@@ -0,0 +1,134 @@
+use crate::config::AnthropicConfig;
+use crate::error::{AIError, AIResult};
+use crate::images::{to_base64, ImageProvider};
+use crate::providers::anthropic::estimate_cost;
+use crate::types::*;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+struct VisionRequest<'a> {
+    model: &'a str,
+    max_tokens: u32,
+    messages: Vec<VisionMessage>,
+}
+
+#[derive(Debug, Serialize)]
+struct VisionMessage {
+    role: &'static str,
+    content: Vec<VisionContent>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum VisionContent {
+    Image { source: ImageSource },
+    Text { text: String },
+}
+
+#[derive(Debug, Serialize)]
+struct ImageSource {
+    #[serde(rename = "type")]
+    source_type: &'static str,
+    media_type: String,
+    data: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct VisionResponse {
+    content: Vec<VisionResponseContent>,
+    usage: VisionUsage,
+}
+
+#[derive(Debug, Deserialize)]
+struct VisionResponseContent {
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct VisionUsage {
+    input_tokens: u32,
+    output_tokens: u32,
+}
+
+/// Image understanding with Claude. Anthropic does not generate images.
+pub struct AnthropicImageProvider {
+    client: Client,
+    config: AnthropicConfig,
+}
+
+impl AnthropicImageProvider {
+    pub fn new(config: &AnthropicConfig) -> Self {
+        Self {
+            client: Client::new(),
+            config: config.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl ImageProvider for AnthropicImageProvider {
+    async fn analyze_images(&self, request: &ImageAnalysisRequest, images: &[ImageInput]) -> AIResult<ImageAnalysisResult> {
+        let model = request.model.as_deref().unwrap_or(&self.config.default_model);
+        let base_url = self.config.base_url.as_deref().unwrap_or("https://api.anthropic.com");
+
+        // Claude answers best with the images ahead of the question
+        let mut content: Vec<VisionContent> = images
+            .iter()
+            .map(|image| VisionContent::Image {
+                source: ImageSource {
+                    source_type: "base64",
+                    media_type: image.mime_type.clone(),
+                    data: to_base64(&image.data),
+                },
+            })
+            .collect();
+        content.push(VisionContent::Text {
+            text: request.prompt.clone(),
+        });
+
+        let response = self
+            .client
+            .post(&format!("{}/v1/messages", base_url))
+            .header("x-api-key", &self.config.api_key)
+            .header("anthropic-version", "2023-06-01")
+            .json(&VisionRequest {
+                model,
+                max_tokens: request.max_tokens.unwrap_or(self.config.max_tokens),
+                messages: vec![VisionMessage { role: "user", content }],
+            })
+            .send()
+            .await
+            .map_err(|e| AIError::HttpClient(e))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(AIError::AIProvider(format!("Anthropic API error: {}", error_text)));
+        }
+
+        let response = response
+            .json::<VisionResponse>()
+            .await
+            .map_err(|e| AIError::AIProvider(format!("Failed to parse Anthropic response: {}", e)))?;
+
+        let analysis = response.content.iter().map(|content| content.text.as_str()).collect();
+        let usage = response.usage;
+
+        Ok(ImageAnalysisResult {
+            analysis,
+            model: model.to_string(),
+            usage: TokenUsage {
+                prompt_tokens: usage.input_tokens,
+                completion_tokens: usage.output_tokens,
+                total_tokens: usage.input_tokens + usage.output_tokens,
+                estimated_cost: estimate_cost(usage.input_tokens, usage.output_tokens),
+            },
+        })
+    }
+
+    fn get_provider_type(&self) -> crate::types::AIProvider {
+        crate::types::AIProvider::Anthropic
+    }
+}
@@ -0,0 +1,123 @@
+use crate::config::LocalAIConfig;
+use crate::error::{AIError, AIResult};
+use crate::images::{to_base64, ImageProvider};
+use crate::types::*;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+struct OllamaChatRequest<'a> {
+    model: &'a str,
+    messages: Vec<OllamaMessage>,
+    stream: bool,
+    options: OllamaOptions,
+    keep_alive: &'a str,
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaMessage {
+    role: &'static str,
+    content: String,
+    images: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct OllamaOptions {
+    num_predict: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_gpu: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_ctx: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaChatResponse {
+    message: Option<OllamaResponseMessage>,
+    #[serde(default)]
+    prompt_eval_count: u32,
+    #[serde(default)]
+    eval_count: u32,
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OllamaResponseMessage {
+    #[serde(default)]
+    content: String,
+}
+
+/// Image understanding with a multimodal model served by Ollama, such as
+/// LLaVA. Ollama does not generate images.
+pub struct LocalImageProvider {
+    client: Client,
+    config: LocalAIConfig,
+}
+
+impl LocalImageProvider {
+    pub fn new(config: &LocalAIConfig) -> Self {
+        Self {
+            client: Client::new(),
+            config: config.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl ImageProvider for LocalImageProvider {
+    async fn analyze_images(&self, request: &ImageAnalysisRequest, images: &[ImageInput]) -> AIResult<ImageAnalysisResult> {
+        let model = request.model.as_deref().unwrap_or(&self.config.vision_model);
+        let tag = self.config.model_tags.get(model).map(|tag| tag.as_str()).unwrap_or(model);
+        let resources = &self.config.resources;
+
+        let response = self
+            .client
+            .post(&format!("{}/api/chat", self.config.base_url.trim_end_matches('/')))
+            .json(&OllamaChatRequest {
+                model: tag,
+                messages: vec![OllamaMessage {
+                    role: "user",
+                    content: request.prompt.clone(),
+                    images: images.iter().map(|image| to_base64(&image.data)).collect(),
+                }],
+                stream: false,
+                options: OllamaOptions {
+                    num_predict: request.max_tokens.unwrap_or(1000),
+                    num_gpu: resources.num_gpu,
+                    num_ctx: resources.num_ctx,
+                },
+                keep_alive: &resources.keep_alive,
+            })
+            .send()
+            .await
+            .map_err(|e| AIError::HttpClient(e))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(AIError::AIProvider(format!("Local AI error: {}", error_text)));
+        }
+
+        let response = response
+            .json::<OllamaChatResponse>()
+            .await
+            .map_err(|e| AIError::AIProvider(format!("Failed to parse Local AI response: {}", e)))?;
+        if let Some(error) = response.error {
+            return Err(AIError::AIProvider(format!("Local AI error: {}", error)));
+        }
+
+        Ok(ImageAnalysisResult {
+            analysis: response.message.map(|message| message.content).unwrap_or_default(),
+            model: model.to_string(),
+            usage: TokenUsage {
+                prompt_tokens: response.prompt_eval_count,
+                completion_tokens: response.eval_count,
+                total_tokens: response.prompt_eval_count + response.eval_count,
+                estimated_cost: 0.0, // Local models have no API cost
+            },
+        })
+    }
+
+    fn get_provider_type(&self) -> crate::types::AIProvider {
+        crate::types::AIProvider::Local
+    }
+}
@@ -0,0 +1,96 @@
+pub mod anthropic;
+pub mod local;
+pub mod openai;
+pub mod preview;
+
+use crate::error::{AIError, AIResult};
+use crate::types::*;
+use async_trait::async_trait;
+use base64::Engine;
+
+#[async_trait]
+pub trait ImageProvider: Send + Sync {
+    /// Providers that only understand images do not generate them
+    async fn generate_images(&self, _request: &ImageGenerationRequest) -> AIResult<GeneratedImages> {
+        Err(AIError::ModelNotAvailable(format!(
+            "{:?} does not generate images",
+            self.get_provider_type()
+        )))
+    }
+
+    async fn analyze_images(&self, request: &ImageAnalysisRequest, images: &[ImageInput]) -> AIResult<ImageAnalysisResult>;
+    fn get_provider_type(&self) -> crate::types::AIProvider;
+}
+
+pub struct ImageProviderManager {
+    openai: Option<openai::OpenAIImageProvider>,
+    anthropic: Option<anthropic::AnthropicImageProvider>,
+    local: Option<local::LocalImageProvider>,
+}
+
+impl ImageProviderManager {
+    pub fn new(config: &crate::config::AIProvidersConfig) -> Self {
+        let openai = if !config.openai.api_key.is_empty() {
+            Some(openai::OpenAIImageProvider::new(&config.openai))
+        } else {
+            None
+        };
+
+        let anthropic = if !config.anthropic.api_key.is_empty() {
+            Some(anthropic::AnthropicImageProvider::new(&config.anthropic))
+        } else {
+            None
+        };
+
+        let local = if config.local.enabled {
+            Some(local::LocalImageProvider::new(&config.local))
+        } else {
+            None
+        };
+
+        Self { openai, anthropic, local }
+    }
+
+    pub fn get_provider(&self, provider_type: &crate::types::AIProvider) -> AIResult<&dyn ImageProvider> {
+        match provider_type {
+            crate::types::AIProvider::OpenAI => {
+                self.openai.as_ref()
+                    .map(|p| p as &dyn ImageProvider)
+                    .ok_or_else(|| AIError::AIProvider("OpenAI images not configured".to_string()))
+            }
+            crate::types::AIProvider::Anthropic => {
+                self.anthropic.as_ref()
+                    .map(|p| p as &dyn ImageProvider)
+                    .ok_or_else(|| AIError::AIProvider("Anthropic images not configured".to_string()))
+            }
+            crate::types::AIProvider::Local => {
+                self.local.as_ref()
+                    .map(|p| p as &dyn ImageProvider)
+                    .ok_or_else(|| AIError::AIProvider("Local images not configured".to_string()))
+            }
+            provider => {
+                Err(AIError::ModelNotAvailable(format!("{:?} images are not supported", provider)))
+            }
+        }
+    }
+
+    /// The provider used when a request does not name one. Only OpenAI
+    /// generates images, so it comes first.
+    pub fn default_provider(&self) -> AIResult<&dyn ImageProvider> {
+        self.openai.as_ref()
+            .map(|p| p as &dyn ImageProvider)
+            .or_else(|| self.anthropic.as_ref().map(|p| p as &dyn ImageProvider))
+            .or_else(|| self.local.as_ref().map(|p| p as &dyn ImageProvider))
+            .ok_or_else(|| AIError::AIProvider("No image provider configured".to_string()))
+    }
+}
+
+pub(crate) fn to_base64(data: &[u8]) -> String {
+    base64::engine::general_purpose::STANDARD.encode(data)
+}
+
+pub(crate) fn from_base64(data: &str) -> AIResult<Vec<u8>> {
+    base64::engine::general_purpose::STANDARD
+        .decode(data)
+        .map_err(|e| AIError::AIProvider(format!("Invalid image data: {}", e)))
+}
@@ -0,0 +1,251 @@
+use crate::config::OpenAIConfig;
+use crate::error::{AIError, AIResult};
+use crate::images::{from_base64, to_base64, ImageProvider};
+use crate::types::*;
+use async_trait::async_trait;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Serialize)]
+struct ImagesRequest<'a> {
+    model: &'a str,
+    prompt: &'a str,
+    n: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    size: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    quality: Option<&'a str>,
+    response_format: &'static str,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImagesResponse {
+    data: Vec<ImageData>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ImageData {
+    b64_json: String,
+    revised_prompt: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+struct VisionRequest<'a> {
+    model: &'a str,
+    max_tokens: u32,
+    messages: Vec<VisionMessage>,
+}
+
+#[derive(Debug, Serialize)]
+struct VisionMessage {
+    role: &'static str,
+    content: Vec<VisionContent>,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum VisionContent {
+    Text { text: String },
+    ImageUrl { image_url: VisionImageUrl },
+}
+
+#[derive(Debug, Serialize)]
+struct VisionImageUrl {
+    url: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct VisionResponse {
+    choices: Vec<VisionChoice>,
+    usage: VisionUsage,
+}
+
+#[derive(Debug, Deserialize)]
+struct VisionChoice {
+    message: VisionChoiceMessage,
+}
+
+#[derive(Debug, Deserialize)]
+struct VisionChoiceMessage {
+    content: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct VisionUsage {
+    prompt_tokens: u32,
+    completion_tokens: u32,
+}
+
+/// DALL-E image generation and GPT-4o vision from the OpenAI API
+pub struct OpenAIImageProvider {
+    client: Client,
+    config: OpenAIConfig,
+}
+
+impl OpenAIImageProvider {
+    pub fn new(config: &OpenAIConfig) -> Self {
+        Self {
+            client: Client::new(),
+            config: config.clone(),
+        }
+    }
+
+    fn url(&self, path: &str) -> String {
+        let base_url = self.config.base_url.as_deref().unwrap_or("https://api.openai.com/v1");
+        format!("{}{}", base_url.trim_end_matches('/'), path)
+    }
+
+    async fn post<T: Serialize>(&self, path: &str, body: &T) -> AIResult<reqwest::Response> {
+        let response = self
+            .client
+            .post(&self.url(path))
+            .bearer_auth(&self.config.api_key)
+            .json(body)
+            .send()
+            .await
+            .map_err(|e| AIError::HttpClient(e))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            if error_text.contains("content_policy_violation") {
+                return Err(AIError::ContentFiltered(format!("OpenAI refused the prompt: {}", error_text)));
+            }
+            return Err(AIError::AIProvider(format!("OpenAI images API error: {}", error_text)));
+        }
+
+        Ok(response)
+    }
+}
+
+/// Price per image of the OpenAI image models
+fn cost_per_image(model: &str, size: Option<&str>, quality: Option<&str>) -> f64 {
+    let hd = quality == Some("hd");
+    match (model, size.unwrap_or("1024x1024")) {
+        ("dall-e-2", "256x256") => 0.016,
+        ("dall-e-2", "512x512") => 0.018,
+        ("dall-e-2", _) => 0.02,
+        (_, "1024x1024") if hd => 0.08,
+        (_, "1024x1024") => 0.04,
+        (_, _) if hd => 0.12,
+        _ => 0.08, // dall-e-3 at 1792x1024 or 1024x1792
+    }
+}
+
+/// Price per input and output token of the OpenAI vision models
+fn vision_token_costs(model: &str) -> (f64, f64) {
+    match model {
+        m if m.starts_with("gpt-4o-mini") => (0.00000015, 0.0000006),
+        m if m.starts_with("gpt-4o") => (0.0000025, 0.00001),
+        _ => (0.00001, 0.00003), // gpt-4-turbo
+    }
+}
+
+#[async_trait]
+impl ImageProvider for OpenAIImageProvider {
+    async fn generate_images(&self, request: &ImageGenerationRequest) -> AIResult<GeneratedImages> {
+        let model = request.model.as_deref().unwrap_or(&self.config.image_model);
+        let count = request.count.unwrap_or(1);
+
+        let mut images = Vec::with_capacity(count as usize);
+        while (images.len() as u32) < count {
+            // DALL-E 3 draws one image per request
+            let remaining = count - images.len() as u32;
+            let n = if model == "dall-e-2" { remaining } else { 1 };
+            let response = self
+                .post(
+                    "/images/generations",
+                    &ImagesRequest {
+                        model,
+                        prompt: &request.prompt,
+                        n,
+                        size: request.size.as_deref(),
+                        quality: request.quality.as_deref(),
+                        response_format: "b64_json",
+                    },
+                )
+                .await?
+                .json::<ImagesResponse>()
+                .await
+                .map_err(|e| AIError::AIProvider(format!("Failed to parse OpenAI images response: {}", e)))?;
+
+            if response.data.is_empty() {
+                return Err(AIError::AIProvider("No image in OpenAI images response".to_string()));
+            }
+            for image in response.data {
+                images.push(GeneratedImage {
+                    data: from_base64(&image.b64_json)?,
+                    mime_type: "image/png".to_string(),
+                    revised_prompt: image.revised_prompt,
+                });
+            }
+        }
+
+        // Tokens of the prompt are estimated for usage reporting
+        let prompt_tokens = (request.prompt.len() / 4) as u32;
+        let estimated_cost = images.len() as f64
+            * cost_per_image(model, request.size.as_deref(), request.quality.as_deref());
+
+        Ok(GeneratedImages {
+            images,
+            model: model.to_string(),
+            usage: TokenUsage {
+                prompt_tokens,
+                completion_tokens: 0,
+                total_tokens: prompt_tokens,
+                estimated_cost,
+            },
+        })
+    }
+
+    async fn analyze_images(&self, request: &ImageAnalysisRequest, images: &[ImageInput]) -> AIResult<ImageAnalysisResult> {
+        let model = request.model.as_deref().unwrap_or(&self.config.vision_model);
+
+        let mut content = vec![VisionContent::Text {
+            text: request.prompt.clone(),
+        }];
+        content.extend(images.iter().map(|image| VisionContent::ImageUrl {
+            image_url: VisionImageUrl {
+                url: format!("data:{};base64,{}", image.mime_type, to_base64(&image.data)),
+            },
+        }));
+
+        let response = self
+            .post(
+                "/chat/completions",
+                &VisionRequest {
+                    model,
+                    max_tokens: request.max_tokens.unwrap_or(self.config.max_tokens),
+                    messages: vec![VisionMessage { role: "user", content }],
+                },
+            )
+            .await?
+            .json::<VisionResponse>()
+            .await
+            .map_err(|e| AIError::AIProvider(format!("Failed to parse OpenAI vision response: {}", e)))?;
+
+        let analysis = response
+            .choices
+            .into_iter()
+            .next()
+            .and_then(|choice| choice.message.content)
+            .ok_or_else(|| AIError::AIProvider("No analysis in OpenAI vision response".to_string()))?;
+
+        let (input_cost, output_cost) = vision_token_costs(model);
+        let usage = response.usage;
+
+        Ok(ImageAnalysisResult {
+            analysis,
+            model: model.to_string(),
+            usage: TokenUsage {
+                prompt_tokens: usage.prompt_tokens,
+                completion_tokens: usage.completion_tokens,
+                total_tokens: usage.prompt_tokens + usage.completion_tokens,
+                estimated_cost: usage.prompt_tokens as f64 * input_cost + usage.completion_tokens as f64 * output_cost,
+            },
+        })
+    }
+
+    fn get_provider_type(&self) -> crate::types::AIProvider {
+        crate::types::AIProvider::OpenAI
+    }
+}
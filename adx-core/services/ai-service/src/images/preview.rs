@@ -0,0 +1,233 @@
+use flate2::read::ZlibDecoder;
+use flate2::write::ZlibEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+
+const PNG_SIGNATURE: [u8; 8] = [0x89, b'P', b'N', b'G', b'\r', b'\n', 0x1a, b'\n'];
+
+/// A decoded 8-bit PNG
+struct Bitmap {
+    width: usize,
+    height: usize,
+    color_type: u8,
+    channels: usize,
+    pixels: Vec<u8>,
+}
+
+/// A PNG no larger than `max_edge` pixels on its longer side, scaled down
+/// from `png`. Image APIs return 8-bit PNGs, which is all this decodes;
+/// `None` when the image is already small enough or cannot be decoded.
+pub fn png_preview(png: &[u8], max_edge: u32) -> Option<Vec<u8>> {
+    let bitmap = decode(png)?;
+    let max_edge = max_edge.max(1) as usize;
+    if bitmap.width.max(bitmap.height) <= max_edge {
+        return None;
+    }
+
+    encode(&scale(&bitmap, max_edge))
+}
+
+fn decode(png: &[u8]) -> Option<Bitmap> {
+    let mut rest = png.strip_prefix(&PNG_SIGNATURE)?;
+    let mut header = None;
+    let mut compressed = Vec::new();
+
+    while rest.len() >= 12 {
+        let length = u32::from_be_bytes(rest[0..4].try_into().ok()?) as usize;
+        let chunk_type = &rest[4..8];
+        let data = rest.get(8..8 + length)?;
+        match chunk_type {
+            b"IHDR" if data.len() == 13 => header = Some(data.to_vec()),
+            b"IDAT" => compressed.extend_from_slice(data),
+            b"IEND" => break,
+            _ => {}
+        }
+        rest = rest.get(12 + length..)?;
+    }
+
+    let header = header?;
+    let width = u32::from_be_bytes(header[0..4].try_into().ok()?) as usize;
+    let height = u32::from_be_bytes(header[4..8].try_into().ok()?) as usize;
+    let (bit_depth, color_type, interlace) = (header[8], header[9], header[12]);
+    let channels = match color_type {
+        0 => 1, // Grayscale
+        2 => 3, // RGB
+        4 => 2, // Grayscale and alpha
+        6 => 4, // RGBA
+        _ => return None, // Palette images are not produced by image APIs
+    };
+    if bit_depth != 8 || interlace != 0 || width == 0 || height == 0 {
+        return None;
+    }
+
+    let stride = width.checked_mul(channels)?;
+    let expected = stride.checked_add(1)?.checked_mul(height)?;
+    let mut filtered = Vec::with_capacity(expected);
+    ZlibDecoder::new(compressed.as_slice())
+        .take(expected as u64)
+        .read_to_end(&mut filtered)
+        .ok()?;
+    if filtered.len() != expected {
+        return None;
+    }
+
+    let mut pixels = vec![0u8; stride * height];
+    for y in 0..height {
+        let row = &filtered[y * (stride + 1)..(y + 1) * (stride + 1)];
+        let (filter, row) = (row[0], &row[1..]);
+        let (done, current) = pixels.split_at_mut(y * stride);
+        let previous = if y > 0 { Some(&done[(y - 1) * stride..]) } else { None };
+        unfilter(filter, channels, row, previous, &mut current[..stride])?;
+    }
+
+    Some(Bitmap {
+        width,
+        height,
+        color_type,
+        channels,
+        pixels,
+    })
+}
+
+/// Undo one of the five PNG row filters
+fn unfilter(filter: u8, channels: usize, row: &[u8], previous: Option<&[u8]>, out: &mut [u8]) -> Option<()> {
+    for i in 0..row.len() {
+        let left = if i >= channels { out[i - channels] } else { 0 };
+        let up = previous.map_or(0, |previous| previous[i]);
+        let up_left = match previous {
+            Some(previous) if i >= channels => previous[i - channels],
+            _ => 0,
+        };
+        let predicted = match filter {
+            0 => 0,
+            1 => left,
+            2 => up,
+            3 => ((left as u16 + up as u16) / 2) as u8,
+            4 => paeth(left, up, up_left),
+            _ => return None,
+        };
+        out[i] = row[i].wrapping_add(predicted);
+    }
+    Some(())
+}
+
+fn paeth(left: u8, up: u8, up_left: u8) -> u8 {
+    let estimate = left as i16 + up as i16 - up_left as i16;
+    let (to_left, to_up, to_up_left) = (
+        (estimate - left as i16).abs(),
+        (estimate - up as i16).abs(),
+        (estimate - up_left as i16).abs(),
+    );
+    if to_left <= to_up && to_left <= to_up_left {
+        left
+    } else if to_up <= to_up_left {
+        up
+    } else {
+        up_left
+    }
+}
+
+/// Scale down by averaging the source pixels each preview pixel covers
+fn scale(bitmap: &Bitmap, max_edge: usize) -> Bitmap {
+    let longer = bitmap.width.max(bitmap.height);
+    let width = (bitmap.width * max_edge / longer).max(1);
+    let height = (bitmap.height * max_edge / longer).max(1);
+    let channels = bitmap.channels;
+
+    let mut pixels = Vec::with_capacity(width * height * channels);
+    for y in 0..height {
+        let (top, bottom) = span(y, height, bitmap.height);
+        for x in 0..width {
+            let (left, right) = span(x, width, bitmap.width);
+            let mut sums = [0u64; 4];
+            for source_y in top..bottom {
+                for source_x in left..right {
+                    let offset = (source_y * bitmap.width + source_x) * channels;
+                    for (sum, value) in sums.iter_mut().zip(&bitmap.pixels[offset..offset + channels]) {
+                        *sum += *value as u64;
+                    }
+                }
+            }
+            let count = ((bottom - top) * (right - left)) as u64;
+            pixels.extend(sums[..channels].iter().map(|sum| (sum / count) as u8));
+        }
+    }
+
+    Bitmap {
+        width,
+        height,
+        color_type: bitmap.color_type,
+        channels,
+        pixels,
+    }
+}
+
+/// The source pixels covered by preview pixel `i` along one side
+fn span(i: usize, preview_len: usize, source_len: usize) -> (usize, usize) {
+    let start = i * source_len / preview_len;
+    let end = ((i + 1) * source_len / preview_len).max(start + 1);
+    (start, end)
+}
+
+fn encode(bitmap: &Bitmap) -> Option<Vec<u8>> {
+    let stride = bitmap.width * bitmap.channels;
+    let mut encoder = ZlibEncoder::new(Vec::new(), Compression::default());
+    for row in bitmap.pixels.chunks(stride) {
+        encoder.write_all(&[0]).ok()?; // No filter
+        encoder.write_all(row).ok()?;
+    }
+    let compressed = encoder.finish().ok()?;
+
+    let mut header = Vec::with_capacity(13);
+    header.extend_from_slice(&(bitmap.width as u32).to_be_bytes());
+    header.extend_from_slice(&(bitmap.height as u32).to_be_bytes());
+    header.extend_from_slice(&[8, bitmap.color_type, 0, 0, 0]);
+
+    let mut png = PNG_SIGNATURE.to_vec();
+    write_chunk(&mut png, b"IHDR", &header);
+    write_chunk(&mut png, b"IDAT", &compressed);
+    write_chunk(&mut png, b"IEND", &[]);
+    Some(png)
+}
+
+fn write_chunk(png: &mut Vec<u8>, chunk_type: &[u8; 4], data: &[u8]) {
+    let mut crc = crc32fast::Hasher::new();
+    crc.update(chunk_type);
+    crc.update(data);
+
+    png.extend_from_slice(&(data.len() as u32).to_be_bytes());
+    png.extend_from_slice(chunk_type);
+    png.extend_from_slice(data);
+    png.extend_from_slice(&crc.finalize().to_be_bytes());
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn solid_png(width: usize, height: usize, rgba: [u8; 4]) -> Vec<u8> {
+        encode(&Bitmap {
+            width,
+            height,
+            color_type: 6,
+            channels: 4,
+            pixels: rgba.repeat(width * height),
+        })
+        .unwrap()
+    }
+
+    #[test]
+    fn test_png_preview_scales_longer_side_to_max_edge() {
+        let preview = png_preview(&solid_png(1024, 512, [10, 20, 30, 255]), 256).unwrap();
+        let bitmap = decode(&preview).unwrap();
+
+        assert_eq!((bitmap.width, bitmap.height), (256, 128));
+        assert_eq!(&bitmap.pixels[..4], &[10, 20, 30, 255]);
+    }
+
+    #[test]
+    fn test_png_preview_skips_small_and_unsupported_images() {
+        assert!(png_preview(&solid_png(200, 100, [0, 0, 0, 255]), 256).is_none());
+        assert!(png_preview(b"\xff\xd8\xff\xe0 not a png", 256).is_none());
+    }
+}
@@ -1,12 +1,18 @@
 pub mod activities;
+pub mod audio;
 pub mod config;
+pub mod embeddings;
 pub mod error;
+pub mod files;
 pub mod handlers;
+pub mod images;
 pub mod models;
+pub mod moderation;
+pub mod pii;
 pub mod providers;
 pub mod server;
 pub mod services;
-pub mod temporal_stubs;
+pub mod temporal;
 pub mod types;
 pub mod workflows;
 pub mod worker;
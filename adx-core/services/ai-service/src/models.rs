@@ -106,6 +106,131 @@ impl AIModelRegistry {
             tier_availability: vec![SubscriptionTier::Enterprise],
         });
         
+        // Azure OpenAI Models (model ids map to deployments in the configuration)
+        self.register_model(AIModel {
+            id: "azure-gpt-35-turbo".to_string(),
+            name: "GPT-3.5 Turbo (Azure)".to_string(),
+            provider: AIProvider::AzureOpenAI,
+            capabilities: vec![
+                AICapability::TextGeneration,
+                AICapability::TextClassification,
+                AICapability::TextSummarization,
+                AICapability::EntityExtraction,
+                AICapability::SentimentAnalysis,
+            ],
+            max_tokens: 4096,
+            cost_per_token: 0.0000015, // $0.0015 per 1K tokens
+            tier_availability: vec![
+                SubscriptionTier::Professional,
+                SubscriptionTier::Enterprise,
+            ],
+        });
+        
+        self.register_model(AIModel {
+            id: "azure-gpt-4".to_string(),
+            name: "GPT-4 (Azure)".to_string(),
+            provider: AIProvider::AzureOpenAI,
+            capabilities: vec![
+                AICapability::TextGeneration,
+                AICapability::TextClassification,
+                AICapability::TextSummarization,
+                AICapability::EntityExtraction,
+                AICapability::SentimentAnalysis,
+                AICapability::CodeGeneration,
+            ],
+            max_tokens: 8192,
+            cost_per_token: 0.00003, // $0.03 per 1K tokens
+            tier_availability: vec![SubscriptionTier::Enterprise],
+        });
+        
+        // AWS Bedrock Models
+        self.register_model(AIModel {
+            id: "anthropic.claude-3-haiku-20240307-v1:0".to_string(),
+            name: "Claude 3 Haiku (Bedrock)".to_string(),
+            provider: AIProvider::Bedrock,
+            capabilities: vec![
+                AICapability::TextGeneration,
+                AICapability::TextClassification,
+                AICapability::TextSummarization,
+                AICapability::EntityExtraction,
+            ],
+            max_tokens: 4096,
+            cost_per_token: 0.00000025, // $0.25 per 1M tokens
+            tier_availability: vec![
+                SubscriptionTier::Professional,
+                SubscriptionTier::Enterprise,
+            ],
+        });
+        
+        self.register_model(AIModel {
+            id: "anthropic.claude-3-sonnet-20240229-v1:0".to_string(),
+            name: "Claude 3 Sonnet (Bedrock)".to_string(),
+            provider: AIProvider::Bedrock,
+            capabilities: vec![
+                AICapability::TextGeneration,
+                AICapability::TextClassification,
+                AICapability::TextSummarization,
+                AICapability::EntityExtraction,
+                AICapability::SentimentAnalysis,
+                AICapability::CodeGeneration,
+            ],
+            max_tokens: 4096,
+            cost_per_token: 0.000003, // $3 per 1M tokens
+            tier_availability: vec![SubscriptionTier::Enterprise],
+        });
+        
+        self.register_model(AIModel {
+            id: "meta.llama3-70b-instruct-v1:0".to_string(),
+            name: "Llama 3 70B Instruct (Bedrock)".to_string(),
+            provider: AIProvider::Bedrock,
+            capabilities: vec![
+                AICapability::TextGeneration,
+                AICapability::TextClassification,
+                AICapability::TextSummarization,
+                AICapability::CodeGeneration,
+            ],
+            max_tokens: 8192,
+            cost_per_token: 0.00000265, // $2.65 per 1M tokens
+            tier_availability: vec![SubscriptionTier::Enterprise],
+        });
+        
+        // Google Gemini Models
+        self.register_model(AIModel {
+            id: "gemini-1.5-flash".to_string(),
+            name: "Gemini 1.5 Flash".to_string(),
+            provider: AIProvider::Gemini,
+            capabilities: vec![
+                AICapability::TextGeneration,
+                AICapability::TextClassification,
+                AICapability::TextSummarization,
+                AICapability::EntityExtraction,
+                AICapability::SentimentAnalysis,
+            ],
+            max_tokens: 1048576,
+            cost_per_token: 0.000000075, // $0.075 per 1M tokens
+            tier_availability: vec![
+                SubscriptionTier::Professional,
+                SubscriptionTier::Enterprise,
+            ],
+        });
+        
+        self.register_model(AIModel {
+            id: "gemini-1.5-pro".to_string(),
+            name: "Gemini 1.5 Pro".to_string(),
+            provider: AIProvider::Gemini,
+            capabilities: vec![
+                AICapability::TextGeneration,
+                AICapability::TextClassification,
+                AICapability::TextSummarization,
+                AICapability::EntityExtraction,
+                AICapability::SentimentAnalysis,
+                AICapability::CodeGeneration,
+            ],
+            max_tokens: 2097152,
+            cost_per_token: 0.00000125, // $1.25 per 1M tokens
+            tier_availability: vec![SubscriptionTier::Enterprise],
+        });
+        
         // Local/Open Source Models (for development and cost-effective options)
         self.register_model(AIModel {
             id: "llama2-7b".to_string(),
@@ -143,6 +268,34 @@ impl AIModelRegistry {
         });
     }
     
+    /// Register locally served models the registry does not know. They
+    /// cost nothing to run, so every tier gets them.
+    pub fn register_local_models(&mut self, models: &[String]) {
+        for model in models {
+            if self.models.contains_key(model) {
+                continue;
+            }
+            
+            self.register_model(AIModel {
+                id: model.clone(),
+                name: model.clone(),
+                provider: AIProvider::Local,
+                capabilities: vec![
+                    AICapability::TextGeneration,
+                    AICapability::TextClassification,
+                    AICapability::TextSummarization,
+                ],
+                max_tokens: 4096,
+                cost_per_token: 0.0,
+                tier_availability: vec![
+                    SubscriptionTier::Free,
+                    SubscriptionTier::Professional,
+                    SubscriptionTier::Enterprise,
+                ],
+            });
+        }
+    }
+    
     pub fn register_model(&mut self, model: AIModel) {
         self.models.insert(model.id.clone(), model);
     }
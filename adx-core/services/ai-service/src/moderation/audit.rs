@@ -0,0 +1,104 @@
+use crate::error::{AIError, AIResult};
+use crate::types::*;
+use serde_json::json;
+
+/// Sends moderation events and audited AI interactions to security-service's
+/// audit log
+#[derive(Clone)]
+pub struct SecurityEventClient {
+    client: reqwest::Client,
+    security_service_url: String,
+}
+
+impl SecurityEventClient {
+    pub fn new(security_service_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            security_service_url: security_service_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Log the findings of a moderation check. Sending happens in the
+    /// background; a failure is logged and does not affect the request.
+    pub fn log_moderation(
+        &self,
+        context: &RequestContext,
+        stage: ModerationStage,
+        findings: &[ModerationFinding],
+        blocked: bool,
+    ) {
+        // Shaped as security-service's CreateAuditLogRequest
+        let event = json!({
+            "tenant_id": context.tenant_id,
+            "user_id": context.user_id,
+            "session_id": context.session_id,
+            "event_type": "ai_content_moderation",
+            "event_category": "Security",
+            "resource_type": "ai_request",
+            "resource_id": context.workflow_id,
+            "action": match stage {
+                ModerationStage::Input => "moderate_input",
+                ModerationStage::Output => "moderate_output",
+            },
+            "outcome": if blocked { "Failure" } else { "Warning" },
+            "ip_address": null,
+            "user_agent": null,
+            "request_id": context.activity_id,
+            "details": {
+                "stage": stage,
+                "blocked": blocked,
+                "findings": findings,
+            },
+        });
+
+        let client = self.client.clone();
+        let url = format!("{}/api/v1/audit/events", self.security_service_url);
+        tokio::spawn(async move {
+            let result = client.post(&url).json(&event).send().await;
+            match result {
+                Ok(response) if response.status().is_success() => {}
+                Ok(response) => tracing::warn!("Security service rejected moderation event: {}", response.status()),
+                Err(e) => tracing::warn!("Failed to send moderation event to security service: {}", e),
+            }
+        });
+    }
+
+    /// Send an audited AI interaction, waiting for security-service to
+    /// accept it
+    pub async fn export_interaction(&self, record: &AuditRecord) -> AIResult<()> {
+        // Shaped as security-service's CreateAuditLogRequest
+        let event = json!({
+            "tenant_id": record.tenant_id,
+            "user_id": record.user_id,
+            "session_id": null,
+            "event_type": "ai_interaction",
+            "event_category": "DataAccess",
+            "resource_type": "ai_request",
+            "resource_id": record.id,
+            "action": record.capability,
+            "outcome": match record.outcome {
+                AuditOutcome::Success => "Success",
+                AuditOutcome::Blocked | AuditOutcome::Failed => "Failure",
+            },
+            "ip_address": null,
+            "user_agent": null,
+            "request_id": record.workflow_id,
+            "details": record,
+        });
+
+        let response = self
+            .client
+            .post(format!("{}/api/v1/audit/events", self.security_service_url))
+            .json(&event)
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(AIError::Internal(format!(
+                "Security service returned {} for audit record {}",
+                response.status(),
+                record.id
+            )));
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,213 @@
+pub mod audit;
+pub mod policies;
+pub mod provider;
+pub mod rules;
+
+pub use audit::SecurityEventClient;
+pub use policies::ModerationPolicyStore;
+pub use provider::OpenAIModeration;
+
+use crate::config::Config;
+use crate::error::{AIError, AIResult};
+use crate::pii::PiiRedactor;
+use crate::types::*;
+use adx_shared::audit::AuditEmitter;
+use adx_shared::dlp::{DlpAction, DlpChannel, DlpClient};
+use sqlx::PgPool;
+use std::sync::Arc;
+
+/// Checks AI inputs before generation and outputs after it against the
+/// tenant's moderation policy: the provider's moderation API, blocked
+/// keywords and PII rules. Findings are blocked, redacted or flagged as the
+/// policy says, and reported to security-service. Inputs are first checked
+/// against the tenant's DLP rules and cleared of PII under its PII policy,
+/// and tokens in outputs restored.
+pub struct Moderator {
+    policies: ModerationPolicyStore,
+    pii: Arc<PiiRedactor>,
+    provider: Option<OpenAIModeration>,
+    security_events: SecurityEventClient,
+    dlp: DlpClient,
+    audit: AuditEmitter,
+}
+
+impl Moderator {
+    pub fn new(config: &Config, db_pool: Arc<PgPool>, pii: Arc<PiiRedactor>) -> Self {
+        let provider = if !config.ai_providers.openai.api_key.is_empty() {
+            Some(OpenAIModeration::new(&config.ai_providers.openai))
+        } else {
+            None
+        };
+
+        Self {
+            policies: ModerationPolicyStore::new(&config.moderation, db_pool),
+            pii,
+            provider,
+            security_events: SecurityEventClient::new(&config.moderation.security_service_url),
+            dlp: DlpClient::from_url(&config.moderation.security_service_url),
+            audit: AuditEmitter::from_url(&config.moderation.security_service_url),
+        }
+    }
+
+    pub fn policies(&self) -> &ModerationPolicyStore {
+        &self.policies
+    }
+
+    pub fn pii(&self) -> &Arc<PiiRedactor> {
+        &self.pii
+    }
+
+    /// The text to use, with redactions applied. Blocked content is
+    /// rejected with `AIError::ContentFiltered`.
+    pub async fn moderate(&self, context: &RequestContext, stage: ModerationStage, text: &str) -> AIResult<ModeratedText> {
+        match stage {
+            ModerationStage::Input => {
+                let (text, dlp_findings) = self.apply_dlp_policy(context, text).await?;
+                let redacted = self.pii.redact_prompt(context, &text).await?;
+                let mut moderated = self.check(context, stage, &redacted.text).await?;
+                moderated.findings.extend(dlp_findings);
+                for detection in redacted.detections {
+                    if !moderated.findings.iter().any(|finding| {
+                        finding.source == ModerationSource::Pii && finding.category == detection.category
+                    }) {
+                        moderated.findings.push(ModerationFinding {
+                            source: ModerationSource::Pii,
+                            category: detection.category,
+                            action: ModerationAction::Redact,
+                        });
+                    }
+                }
+                Ok(moderated)
+            }
+            // Outputs are checked as the model wrote them, so restored
+            // values are not redacted again
+            ModerationStage::Output => {
+                let mut moderated = self.check(context, stage, text).await?;
+                moderated.text = self.pii.restore(&context.tenant_id, &moderated.text).await?;
+                Ok(moderated)
+            }
+        }
+    }
+
+    /// Scan a prompt against the tenant's DLP rules and record an incident
+    /// for what matched. Prompts go ahead when security-service cannot be
+    /// reached.
+    async fn apply_dlp_policy(&self, context: &RequestContext, text: &str) -> AIResult<(String, Vec<ModerationFinding>)> {
+        let result = match self.dlp.scan(&context.tenant_id, DlpChannel::AiPrompt, text).await {
+            Ok(result) => result,
+            Err(e) => {
+                tracing::warn!("Could not load DLP policy of tenant {}: {}", context.tenant_id, e);
+                return Ok((text.to_string(), Vec::new()));
+            }
+        };
+
+        if let Some(incident) = result.incident(
+            &context.tenant_id,
+            "ai-service",
+            "ai_request",
+            context.workflow_id.as_deref(),
+            Some(&context.user_id),
+        ) {
+            self.audit.emit(incident);
+        }
+
+        if result.is_blocked() {
+            return Err(AIError::ContentFiltered(format!(
+                "Input matches data loss prevention rules ({})",
+                result.blocking_rules().join(", ")
+            )));
+        }
+
+        let findings = result
+            .findings
+            .iter()
+            .map(|finding| ModerationFinding {
+                source: ModerationSource::Dlp,
+                category: finding.rule_name.clone(),
+                action: match finding.action {
+                    DlpAction::Block => ModerationAction::Block,
+                    DlpAction::Redact => ModerationAction::Redact,
+                    DlpAction::Alert => ModerationAction::Flag,
+                },
+            })
+            .collect();
+        Ok((result.text, findings))
+    }
+
+    async fn check(&self, context: &RequestContext, stage: ModerationStage, text: &str) -> AIResult<ModeratedText> {
+        let policy = self.policies.get(&context.tenant_id).await?;
+        if !policy.enabled {
+            return Ok(ModeratedText {
+                text: text.to_string(),
+                findings: Vec::new(),
+            });
+        }
+
+        let mut findings = Vec::new();
+        let mut redactions = Vec::new();
+
+        if policy.provider_check {
+            if let Some(provider) = &self.provider {
+                // An unavailable moderation API does not stop requests; the
+                // keyword and PII rules still apply
+                match provider.flagged_categories(text).await {
+                    Ok(categories) => findings.extend(categories.into_iter().map(|category| ModerationFinding {
+                        source: ModerationSource::Provider,
+                        category,
+                        action: policy.provider_action,
+                    })),
+                    Err(e) => tracing::warn!("Moderation API unavailable: {}", e),
+                }
+            }
+        }
+
+        let rule_matches = [
+            (ModerationSource::Keyword, policy.keyword_action, rules::keyword_matches(text, &policy.blocked_keywords)),
+            (ModerationSource::Pii, policy.pii_action, rules::pii_matches(text, &policy.pii_types)),
+        ];
+        for (source, action, matches) in rule_matches {
+            for found in matches {
+                if action == ModerationAction::Redact {
+                    redactions.push(found.range);
+                }
+                if !findings.iter().any(|finding: &ModerationFinding| finding.source == source && finding.category == found.category) {
+                    findings.push(ModerationFinding {
+                        source,
+                        category: found.category,
+                        action,
+                    });
+                }
+            }
+        }
+
+        if findings.is_empty() {
+            return Ok(ModeratedText {
+                text: text.to_string(),
+                findings,
+            });
+        }
+
+        let blocked: Vec<&str> = findings
+            .iter()
+            .filter(|finding| finding.action == ModerationAction::Block)
+            .map(|finding| finding.category.as_str())
+            .collect();
+        self.security_events.log_moderation(context, stage, &findings, !blocked.is_empty());
+
+        if !blocked.is_empty() {
+            return Err(AIError::ContentFiltered(format!(
+                "{} violates the moderation policy ({})",
+                match stage {
+                    ModerationStage::Input => "Input",
+                    ModerationStage::Output => "Generated output",
+                },
+                blocked.join(", ")
+            )));
+        }
+
+        Ok(ModeratedText {
+            text: rules::redact(text, &redactions),
+            findings,
+        })
+    }
+}
@@ -0,0 +1,116 @@
+use crate::config::ModerationConfig;
+use crate::error::{AIError, AIResult};
+use crate::types::*;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use std::sync::Arc;
+
+/// Tenants' moderation policies. Tenants without one get the policy built
+/// from the service configuration.
+pub struct ModerationPolicyStore {
+    db_pool: Arc<PgPool>,
+    default_policy: ModerationPolicy,
+}
+
+impl ModerationPolicyStore {
+    pub fn new(config: &ModerationConfig, db_pool: Arc<PgPool>) -> Self {
+        Self {
+            db_pool,
+            default_policy: ModerationPolicy {
+                enabled: config.enabled,
+                provider_check: config.provider_check,
+                provider_action: ModerationAction::Block,
+                blocked_keywords: config.blocked_keywords.clone(),
+                keyword_action: ModerationAction::Block,
+                pii_types: Vec::new(),
+                pii_action: ModerationAction::Redact,
+            },
+        }
+    }
+
+    pub async fn get(&self, tenant_id: &str) -> AIResult<ModerationPolicy> {
+        let row = sqlx::query(
+            r#"
+            SELECT enabled, provider_check, provider_action, blocked_keywords, keyword_action, pii_types, pii_action
+            FROM ai_moderation_policies
+            WHERE tenant_id = $1
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_optional(&*self.db_pool)
+        .await?;
+
+        match row {
+            Some(row) => policy_from_row(&row),
+            None => Ok(self.default_policy.clone()),
+        }
+    }
+
+    pub async fn put(&self, tenant_id: &str, policy: &ModerationPolicy) -> AIResult<()> {
+        if policy.provider_action == ModerationAction::Redact {
+            return Err(AIError::Validation(
+                "Provider findings cannot be redacted; provider_action must be block or flag".to_string(),
+            ));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO ai_moderation_policies
+                (tenant_id, enabled, provider_check, provider_action, blocked_keywords, keyword_action, pii_types, pii_action)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (tenant_id) DO UPDATE
+            SET enabled = EXCLUDED.enabled,
+                provider_check = EXCLUDED.provider_check,
+                provider_action = EXCLUDED.provider_action,
+                blocked_keywords = EXCLUDED.blocked_keywords,
+                keyword_action = EXCLUDED.keyword_action,
+                pii_types = EXCLUDED.pii_types,
+                pii_action = EXCLUDED.pii_action
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(policy.enabled)
+        .bind(policy.provider_check)
+        .bind(action_name(policy.provider_action))
+        .bind(serde_json::to_value(&policy.blocked_keywords)?)
+        .bind(action_name(policy.keyword_action))
+        .bind(serde_json::to_value(&policy.pii_types)?)
+        .bind(action_name(policy.pii_action))
+        .execute(&*self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Go back to the default policy
+    pub async fn delete(&self, tenant_id: &str) -> AIResult<()> {
+        sqlx::query("DELETE FROM ai_moderation_policies WHERE tenant_id = $1")
+            .bind(tenant_id)
+            .execute(&*self.db_pool)
+            .await?;
+        Ok(())
+    }
+}
+
+fn action_name(action: ModerationAction) -> &'static str {
+    match action {
+        ModerationAction::Block => "block",
+        ModerationAction::Redact => "redact",
+        ModerationAction::Flag => "flag",
+    }
+}
+
+fn parse_action(name: &str) -> AIResult<ModerationAction> {
+    serde_json::from_value(serde_json::Value::String(name.to_string())).map_err(AIError::from)
+}
+
+fn policy_from_row(row: &PgRow) -> AIResult<ModerationPolicy> {
+    Ok(ModerationPolicy {
+        enabled: row.try_get("enabled")?,
+        provider_check: row.try_get("provider_check")?,
+        provider_action: parse_action(row.try_get("provider_action")?)?,
+        blocked_keywords: serde_json::from_value(row.try_get("blocked_keywords")?)?,
+        keyword_action: parse_action(row.try_get("keyword_action")?)?,
+        pii_types: serde_json::from_value(row.try_get("pii_types")?)?,
+        pii_action: parse_action(row.try_get("pii_action")?)?,
+    })
+}
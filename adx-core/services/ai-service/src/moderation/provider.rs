@@ -0,0 +1,51 @@
+use crate::config::OpenAIConfig;
+use crate::error::{AIError, AIResult};
+use async_openai::{
+    types::{CreateModerationRequest, ModerationInput},
+    Client,
+};
+
+/// Content classification by the OpenAI moderation API
+pub struct OpenAIModeration {
+    client: Client<async_openai::config::OpenAIConfig>,
+}
+
+impl OpenAIModeration {
+    pub fn new(config: &OpenAIConfig) -> Self {
+        let mut openai_config = async_openai::config::OpenAIConfig::new()
+            .with_api_key(&config.api_key);
+
+        if let Some(base_url) = &config.base_url {
+            openai_config = openai_config.with_api_base(base_url);
+        }
+
+        Self {
+            client: Client::with_config(openai_config),
+        }
+    }
+
+    /// Categories the text was flagged for, e.g. `hate` or `self-harm/intent`
+    pub async fn flagged_categories(&self, text: &str) -> AIResult<Vec<String>> {
+        let response = self.client
+            .moderations()
+            .create(CreateModerationRequest {
+                input: ModerationInput::String(text.to_string()),
+                model: None,
+            })
+            .await
+            .map_err(|e| AIError::AIProvider(format!("OpenAI moderation error: {}", e)))?;
+
+        let mut categories = Vec::new();
+        for result in response.results.into_iter().filter(|result| result.flagged) {
+            if let serde_json::Value::Object(flags) = serde_json::to_value(&result.categories)? {
+                categories.extend(
+                    flags
+                        .into_iter()
+                        .filter(|(_, flagged)| flagged.as_bool() == Some(true))
+                        .map(|(category, _)| category),
+                );
+            }
+        }
+        Ok(categories)
+    }
+}
@@ -0,0 +1,144 @@
+use crate::types::PiiType;
+use adx_shared::pii;
+use regex::{Regex, RegexBuilder};
+use std::ops::Range;
+use std::sync::OnceLock;
+
+/// A rule match: what matched, and where
+#[derive(Debug, Clone, PartialEq)]
+pub struct RuleMatch {
+    pub category: String,
+    pub range: Range<usize>,
+}
+
+/// Whole-word, case-insensitive matches of the keywords
+pub fn keyword_matches(text: &str, keywords: &[String]) -> Vec<RuleMatch> {
+    let alternatives: Vec<String> = keywords
+        .iter()
+        .map(|keyword| keyword.trim())
+        .filter(|keyword| !keyword.is_empty())
+        .map(regex::escape)
+        .collect();
+    if alternatives.is_empty() {
+        return Vec::new();
+    }
+
+    let pattern = RegexBuilder::new(&format!(r"\b(?:{})\b", alternatives.join("|")))
+        .case_insensitive(true)
+        .build()
+        .expect("escaped keywords form a valid pattern");
+
+    pattern
+        .find_iter(text)
+        .map(|found| RuleMatch {
+            category: found.as_str().to_lowercase(),
+            range: found.range(),
+        })
+        .collect()
+}
+
+pub fn pii_matches(text: &str, pii_types: &[PiiType]) -> Vec<RuleMatch> {
+    let mut matches = Vec::new();
+    for pii_type in pii_types {
+        for found in pii_pattern(*pii_type).find_iter(text) {
+            if !pii_valid(*pii_type, found.as_str()) {
+                continue;
+            }
+            matches.push(RuleMatch {
+                category: pii_name(*pii_type).to_string(),
+                range: found.range(),
+            });
+        }
+    }
+    matches
+}
+
+/// Replace the ranges, merging overlapping ones, with `[REDACTED]`
+pub fn redact(text: &str, ranges: &[Range<usize>]) -> String {
+    let mut ranges = ranges.to_vec();
+    ranges.sort_by_key(|range| range.start);
+
+    let mut redacted = String::with_capacity(text.len());
+    let mut position = 0;
+    for range in ranges {
+        if range.start < position {
+            // Overlaps the range redacted last
+            position = position.max(range.end);
+            continue;
+        }
+        redacted.push_str(&text[position..range.start]);
+        redacted.push_str("[REDACTED]");
+        position = range.end;
+    }
+    redacted.push_str(&text[position..]);
+    redacted
+}
+
+pub fn pii_name(pii_type: PiiType) -> &'static str {
+    match pii_type {
+        PiiType::Email => "email",
+        PiiType::Phone => "phone",
+        PiiType::CreditCard => "credit_card",
+        PiiType::Ssn => "ssn",
+        PiiType::IpAddress => "ip_address",
+    }
+}
+
+fn pii_pattern(pii_type: PiiType) -> &'static Regex {
+    static EMAIL: OnceLock<Regex> = OnceLock::new();
+    static PHONE: OnceLock<Regex> = OnceLock::new();
+    static IP_ADDRESS: OnceLock<Regex> = OnceLock::new();
+
+    let (cell, pattern) = match pii_type {
+        PiiType::CreditCard => return pii::credit_card_pattern(),
+        PiiType::Ssn => return pii::ssn_pattern(),
+        PiiType::Email => (&EMAIL, r"\b[A-Za-z0-9._%+-]+@[A-Za-z0-9.-]+\.[A-Za-z]{2,}\b"),
+        PiiType::Phone => (&PHONE, r"(?:\+\d{1,3}[\s.-]?)?\(?\b\d{3}\)?[\s.-]?\d{3}[\s.-]?\d{4}\b"),
+        PiiType::IpAddress => (&IP_ADDRESS, r"\b(?:(?:25[0-5]|2[0-4]\d|1?\d?\d)\.){3}(?:25[0-5]|2[0-4]\d|1?\d?\d)\b"),
+    };
+    cell.get_or_init(|| Regex::new(pattern).expect("PII patterns are valid"))
+}
+
+/// Card numbers and SSNs are checked the same way DLP checks them
+fn pii_valid(pii_type: PiiType, found: &str) -> bool {
+    match pii_type {
+        PiiType::CreditCard => pii::luhn_valid(found),
+        PiiType::Ssn => pii::ssn_valid(found),
+        _ => true,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_keyword_matches_whole_words() {
+        let keywords = vec!["hack".to_string()];
+
+        let matches = keyword_matches("How do I Hack this? Join our hackathon.", &keywords);
+        assert_eq!(matches.len(), 1);
+        assert_eq!(matches[0].category, "hack");
+        assert_eq!(matches[0].range, 9..13);
+    }
+
+    #[test]
+    fn test_pii_matches_and_redact() {
+        let text = "Mail jane@example.com or pay with 4111 1111 1111 1111, not 1234 5678 9012 3456.";
+        let matches = pii_matches(text, &[PiiType::Email, PiiType::CreditCard]);
+
+        let categories: Vec<&str> = matches.iter().map(|found| found.category.as_str()).collect();
+        assert_eq!(categories, vec!["email", "credit_card"]);
+
+        let ranges: Vec<Range<usize>> = matches.into_iter().map(|found| found.range).collect();
+        assert_eq!(
+            redact(text, &ranges),
+            "Mail [REDACTED] or pay with [REDACTED], not 1234 5678 9012 3456."
+        );
+    }
+
+    #[test]
+    fn test_redact_overlapping_ranges() {
+        assert_eq!(redact("abcdefgh", &[2..5, 4..6, 0..1]), "[REDACTED]b[REDACTED]gh");
+    }
+}
@@ -0,0 +1,267 @@
+pub mod policies;
+pub mod tokens;
+
+pub use policies::PiiPolicyStore;
+pub use tokens::TokenVault;
+
+use crate::config::{Config, PiiConfig};
+use crate::error::{AIError, AIResult};
+use crate::models::AIModelRegistry;
+use crate::moderation::rules;
+use crate::providers::AIProviderManager;
+use crate::types::*;
+use sqlx::PgPool;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Entities the NER model is less sure of than this are not treated as PII
+const MIN_ENTITY_CONFIDENCE: f32 = 0.5;
+
+/// Finds PII in text, with patterns and with entity extraction on a
+/// self-hosted model, and redacts or tokenizes it as the tenant's policy
+/// says. Prompts go through it before they leave the platform; file-service
+/// and security-service call it through the `/api/v1/pii` endpoints.
+pub struct PiiRedactor {
+    config: PiiConfig,
+    policies: PiiPolicyStore,
+    tokens: TokenVault,
+    provider_manager: Arc<AIProviderManager>,
+    model_registry: Arc<AIModelRegistry>,
+}
+
+impl PiiRedactor {
+    pub fn new(
+        config: &Config,
+        db_pool: Arc<PgPool>,
+        provider_manager: Arc<AIProviderManager>,
+        model_registry: Arc<AIModelRegistry>,
+    ) -> Self {
+        Self {
+            config: config.pii.clone(),
+            policies: PiiPolicyStore::new(&config.pii, db_pool.clone()),
+            tokens: TokenVault::new(db_pool, Duration::from_secs(config.pii.token_ttl_seconds)),
+            provider_manager,
+            model_registry,
+        }
+    }
+
+    pub async fn policy(&self, tenant_id: &str) -> AIResult<PiiPolicy> {
+        self.policies.get(tenant_id).await
+    }
+
+    pub async fn put_policy(&self, tenant_id: &str, policy: &PiiPolicy) -> AIResult<()> {
+        if !policy.entity_types.is_empty() {
+            self.ner_model()?;
+        }
+        self.policies.put(tenant_id, policy).await
+    }
+
+    pub async fn delete_policy(&self, tenant_id: &str) -> AIResult<()> {
+        self.policies.delete(tenant_id).await
+    }
+
+    /// The PII the tenant's policy covers in `text`, in order of position.
+    /// Detections overlapping an earlier one are dropped.
+    pub async fn detect(&self, context: &RequestContext, text: &str) -> AIResult<Vec<PiiDetection>> {
+        let policy = self.policies.get(&context.tenant_id).await?;
+        self.detect_with(context, text, &policy).await
+    }
+
+    /// `text` with the PII the tenant's policy covers redacted or tokenized,
+    /// whether or not the policy applies to prompts
+    pub async fn redact(&self, context: &RequestContext, text: &str) -> AIResult<RedactedText> {
+        let policy = self.policies.get(&context.tenant_id).await?;
+        self.redact_with(context, text, &policy).await
+    }
+
+    /// A prompt as it may leave the platform. Unchanged when the tenant's
+    /// policy is disabled.
+    pub async fn redact_prompt(&self, context: &RequestContext, text: &str) -> AIResult<RedactedText> {
+        let policy = self.policies.get(&context.tenant_id).await?;
+        if !policy.enabled {
+            return Ok(RedactedText {
+                text: text.to_string(),
+                detections: Vec::new(),
+            });
+        }
+        self.redact_with(context, text, &policy).await
+    }
+
+    /// `text` with the tenant's tokens replaced by the values they stand for
+    pub async fn restore(&self, tenant_id: &str, text: &str) -> AIResult<String> {
+        self.tokens.restore(tenant_id, text).await
+    }
+
+    /// Purge expired tokens every `interval`
+    pub fn start_purging(self: Arc<Self>, interval: Duration) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match self.tokens.purge_expired().await {
+                    Ok(0) => {}
+                    Ok(purged) => tracing::info!("Purged {} expired PII tokens", purged),
+                    Err(e) => tracing::warn!("Failed to purge expired PII tokens: {}", e),
+                }
+            }
+        });
+    }
+
+    async fn redact_with(&self, context: &RequestContext, text: &str, policy: &PiiPolicy) -> AIResult<RedactedText> {
+        let detections = self.detect_with(context, text, policy).await?;
+
+        let mut replacements = Vec::with_capacity(detections.len());
+        for detection in &detections {
+            let replacement = match policy.mode {
+                PiiMode::Redact => "[REDACTED]".to_string(),
+                PiiMode::Tokenize => {
+                    self.tokens
+                        .tokenize(&context.tenant_id, &detection.category, &detection.text)
+                        .await?
+                }
+            };
+            replacements.push((detection.start..detection.end, replacement));
+        }
+
+        Ok(RedactedText {
+            text: replace_ranges(text, &replacements),
+            detections,
+        })
+    }
+
+    async fn detect_with(&self, context: &RequestContext, text: &str, policy: &PiiPolicy) -> AIResult<Vec<PiiDetection>> {
+        let mut detections: Vec<PiiDetection> = rules::pii_matches(text, &policy.pii_types)
+            .into_iter()
+            .map(|found| PiiDetection {
+                source: PiiSource::Pattern,
+                category: found.category,
+                text: text[found.range.clone()].to_string(),
+                start: found.range.start,
+                end: found.range.end,
+            })
+            .collect();
+
+        if !policy.entity_types.is_empty() && !text.trim().is_empty() {
+            detections.extend(self.detect_entities(context, text, &policy.entity_types).await?);
+        }
+
+        detections.sort_by_key(|detection| (detection.start, std::cmp::Reverse(detection.end)));
+        let mut position = 0;
+        detections.retain(|detection| {
+            let keep = detection.start >= position;
+            if keep {
+                position = detection.end;
+            }
+            keep
+        });
+        Ok(detections)
+    }
+
+    // Entities found by the NER model. The model reports entity text, which
+    // is located in `text` rather than trusting its positions.
+    async fn detect_entities(&self, context: &RequestContext, text: &str, entity_types: &[EntityType]) -> AIResult<Vec<PiiDetection>> {
+        let model = self.ner_model()?;
+        let provider = self.provider_manager.get_provider(&model.provider)?;
+        let extracted = provider
+            .extract_entities(&EntityExtractionRequest {
+                text: text.to_string(),
+                entity_types: entity_types.to_vec(),
+                model: Some(model.id.clone()),
+                context: context.clone(),
+            })
+            .await?;
+
+        let mut detections = Vec::new();
+        for entity in extracted.entities {
+            let value = entity.text.trim();
+            if value.is_empty() || entity.confidence < MIN_ENTITY_CONFIDENCE {
+                continue;
+            }
+            for (start, found) in text.match_indices(value) {
+                detections.push(PiiDetection {
+                    source: PiiSource::Ner,
+                    category: entity_name(&entity.entity_type),
+                    text: found.to_string(),
+                    start,
+                    end: start + found.len(),
+                });
+            }
+        }
+        Ok(detections)
+    }
+
+    // Entity extraction sends the text to the model, so it must not leave
+    // the platform either
+    fn ner_model(&self) -> AIResult<&AIModel> {
+        let model = self.model_registry.get_model(&self.config.ner_model).ok_or_else(|| {
+            AIError::ModelNotAvailable(format!("PII NER model {} is not available", self.config.ner_model))
+        })?;
+        if !model.provider.is_self_hosted() {
+            return Err(AIError::ModelNotAvailable(format!(
+                "PII NER model {} is not self-hosted",
+                self.config.ner_model
+            )));
+        }
+        Ok(model)
+    }
+}
+
+/// Category name of an entity type, e.g. `person`
+pub fn entity_name(entity_type: &EntityType) -> String {
+    match entity_type {
+        EntityType::Person => "person".to_string(),
+        EntityType::Organization => "organization".to_string(),
+        EntityType::Location => "location".to_string(),
+        EntityType::Date => "date".to_string(),
+        EntityType::Money => "money".to_string(),
+        EntityType::Email => "email".to_string(),
+        EntityType::Phone => "phone".to_string(),
+        EntityType::Url => "url".to_string(),
+        EntityType::Custom(name) => name.to_lowercase().replace(|c: char| !c.is_ascii_alphanumeric(), "_"),
+    }
+}
+
+/// `text` with each range replaced by its replacement. Ranges overlapping an
+/// earlier one are skipped.
+pub fn replace_ranges(text: &str, replacements: &[(Range<usize>, String)]) -> String {
+    let mut replacements: Vec<&(Range<usize>, String)> = replacements.iter().collect();
+    replacements.sort_by_key(|(range, _)| range.start);
+
+    let mut replaced = String::with_capacity(text.len());
+    let mut position = 0;
+    for (range, replacement) in replacements {
+        if range.start < position {
+            continue;
+        }
+        replaced.push_str(&text[position..range.start]);
+        replaced.push_str(replacement);
+        position = range.end;
+    }
+    replaced.push_str(&text[position..]);
+    replaced
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_replace_ranges() {
+        let replacements = vec![
+            (9..13, "[PERSON_00000002]".to_string()),
+            (0..4, "[PERSON_00000001]".to_string()),
+            (10..12, "skipped".to_string()),
+        ];
+        assert_eq!(
+            replace_ranges("Jane met John today", &replacements),
+            "[PERSON_00000001] met [PERSON_00000002] today"
+        );
+    }
+
+    #[test]
+    fn test_entity_name() {
+        assert_eq!(entity_name(&EntityType::Person), "person");
+        assert_eq!(entity_name(&EntityType::Custom("Patient ID".to_string())), "patient_id");
+    }
+}
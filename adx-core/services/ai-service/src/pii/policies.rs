@@ -0,0 +1,109 @@
+use crate::config::PiiConfig;
+use crate::error::{AIError, AIResult};
+use crate::types::*;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use std::sync::Arc;
+
+/// All PII the patterns detect
+const PATTERN_PII_TYPES: [PiiType; 5] = [
+    PiiType::Email,
+    PiiType::Phone,
+    PiiType::CreditCard,
+    PiiType::Ssn,
+    PiiType::IpAddress,
+];
+
+/// Tenants' PII policies. Tenants without one get the policy built from the
+/// service configuration.
+pub struct PiiPolicyStore {
+    db_pool: Arc<PgPool>,
+    default_policy: PiiPolicy,
+}
+
+impl PiiPolicyStore {
+    pub fn new(config: &PiiConfig, db_pool: Arc<PgPool>) -> Self {
+        Self {
+            db_pool,
+            default_policy: PiiPolicy {
+                enabled: config.enabled,
+                pii_types: if config.detect_patterns {
+                    PATTERN_PII_TYPES.to_vec()
+                } else {
+                    Vec::new()
+                },
+                entity_types: Vec::new(),
+                mode: PiiMode::Redact,
+            },
+        }
+    }
+
+    pub async fn get(&self, tenant_id: &str) -> AIResult<PiiPolicy> {
+        let row = sqlx::query(
+            r#"
+            SELECT enabled, pii_types, entity_types, mode
+            FROM ai_pii_policies
+            WHERE tenant_id = $1
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_optional(&*self.db_pool)
+        .await?;
+
+        match row {
+            Some(row) => policy_from_row(&row),
+            None => Ok(self.default_policy.clone()),
+        }
+    }
+
+    pub async fn put(&self, tenant_id: &str, policy: &PiiPolicy) -> AIResult<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO ai_pii_policies (tenant_id, enabled, pii_types, entity_types, mode)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (tenant_id) DO UPDATE
+            SET enabled = EXCLUDED.enabled,
+                pii_types = EXCLUDED.pii_types,
+                entity_types = EXCLUDED.entity_types,
+                mode = EXCLUDED.mode
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(policy.enabled)
+        .bind(serde_json::to_value(&policy.pii_types)?)
+        .bind(serde_json::to_value(&policy.entity_types)?)
+        .bind(mode_name(policy.mode))
+        .execute(&*self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Go back to the default policy
+    pub async fn delete(&self, tenant_id: &str) -> AIResult<()> {
+        sqlx::query("DELETE FROM ai_pii_policies WHERE tenant_id = $1")
+            .bind(tenant_id)
+            .execute(&*self.db_pool)
+            .await?;
+        Ok(())
+    }
+}
+
+fn mode_name(mode: PiiMode) -> &'static str {
+    match mode {
+        PiiMode::Redact => "redact",
+        PiiMode::Tokenize => "tokenize",
+    }
+}
+
+fn parse_mode(name: &str) -> AIResult<PiiMode> {
+    serde_json::from_value(serde_json::Value::String(name.to_string())).map_err(AIError::from)
+}
+
+fn policy_from_row(row: &PgRow) -> AIResult<PiiPolicy> {
+    Ok(PiiPolicy {
+        enabled: row.try_get("enabled")?,
+        pii_types: serde_json::from_value(row.try_get("pii_types")?)?,
+        entity_types: serde_json::from_value(row.try_get("entity_types")?)?,
+        mode: parse_mode(row.try_get("mode")?)?,
+    })
+}
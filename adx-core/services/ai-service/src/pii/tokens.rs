@@ -0,0 +1,115 @@
+use crate::error::AIResult;
+use regex::Regex;
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use std::sync::{Arc, OnceLock};
+use std::time::Duration;
+
+/// Values replaced by tokens, kept so generated text that repeats a token
+/// can be given back its value. A token is derived from the tenant and the
+/// value, so the same value gets the same token in every prompt.
+pub struct TokenVault {
+    db_pool: Arc<PgPool>,
+    ttl: Duration,
+}
+
+impl TokenVault {
+    pub fn new(db_pool: Arc<PgPool>, ttl: Duration) -> Self {
+        Self { db_pool, ttl }
+    }
+
+    /// The token standing in for `value`, e.g. `[EMAIL_3f9a1c2e]`
+    pub async fn tokenize(&self, tenant_id: &str, category: &str, value: &str) -> AIResult<String> {
+        let token = token_for(tenant_id, category, value);
+        sqlx::query(
+            r#"
+            INSERT INTO ai_pii_tokens (tenant_id, token, value, category, expires_at)
+            VALUES ($1, $2, $3, $4, NOW() + make_interval(secs => $5))
+            ON CONFLICT (tenant_id, token) DO UPDATE
+            SET expires_at = EXCLUDED.expires_at
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&token)
+        .bind(value)
+        .bind(category)
+        .bind(self.ttl.as_secs() as f64)
+        .execute(&*self.db_pool)
+        .await?;
+
+        Ok(token)
+    }
+
+    /// `text` with its tokens replaced by their values. Unknown and expired
+    /// tokens are left as they are.
+    pub async fn restore(&self, tenant_id: &str, text: &str) -> AIResult<String> {
+        let mut tokens: Vec<String> = token_pattern().find_iter(text).map(|found| found.as_str().to_string()).collect();
+        if tokens.is_empty() {
+            return Ok(text.to_string());
+        }
+        tokens.sort();
+        tokens.dedup();
+
+        let rows = sqlx::query(
+            r#"
+            SELECT token, value
+            FROM ai_pii_tokens
+            WHERE tenant_id = $1 AND token = ANY($2) AND expires_at > NOW()
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&tokens)
+        .fetch_all(&*self.db_pool)
+        .await?;
+
+        let mut values = HashMap::new();
+        for row in rows {
+            values.insert(row.try_get::<String, _>("token")?, row.try_get::<String, _>("value")?);
+        }
+
+        Ok(token_pattern()
+            .replace_all(text, |found: &regex::Captures| {
+                values.get(&found[0]).cloned().unwrap_or_else(|| found[0].to_string())
+            })
+            .into_owned())
+    }
+
+    /// Delete tokens past their time to live
+    pub async fn purge_expired(&self) -> AIResult<u64> {
+        let result = sqlx::query("DELETE FROM ai_pii_tokens WHERE expires_at <= NOW()")
+            .execute(&*self.db_pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+}
+
+fn token_for(tenant_id: &str, category: &str, value: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(tenant_id.as_bytes());
+    hasher.update([0]);
+    hasher.update(category.as_bytes());
+    hasher.update([0]);
+    hasher.update(value.as_bytes());
+    let digest = hex::encode(hasher.finalize());
+    format!("[{}_{}]", category.to_uppercase(), &digest[..8])
+}
+
+fn token_pattern() -> &'static Regex {
+    static TOKEN: OnceLock<Regex> = OnceLock::new();
+    TOKEN.get_or_init(|| Regex::new(r"\[[A-Z_]+_[0-9a-f]{8}\]").expect("valid token pattern"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tokens_are_stable_per_tenant() {
+        let token = token_for("tenant-a", "credit_card", "4111 1111 1111 1111");
+        assert!(token.starts_with("[CREDIT_CARD_"));
+        assert!(token_pattern().is_match(&token));
+        assert_eq!(token, token_for("tenant-a", "credit_card", "4111 1111 1111 1111"));
+        assert_ne!(token, token_for("tenant-b", "credit_card", "4111 1111 1111 1111"));
+    }
+}
@@ -1,9 +1,10 @@
 use crate::config::AnthropicConfig;
 use crate::error::{AIError, AIResult};
-use crate::providers::AIProvider;
+use crate::providers::{sse_data, AIProvider, TextStream};
 use crate::types::*;
 use async_trait::async_trait;
 use chrono::Utc;
+use futures::StreamExt;
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
@@ -19,6 +20,8 @@ struct AnthropicRequest {
     top_p: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
     stop_sequences: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
 }
 
 #[derive(Debug, Serialize)]
@@ -69,10 +72,7 @@ impl AnthropicProvider {
     }
     
     fn calculate_cost(&self, input_tokens: u32, output_tokens: u32) -> f64 {
-        // Anthropic pricing is typically different for input vs output tokens
-        // For simplicity, using average cost per token
-        let total_tokens = input_tokens + output_tokens;
-        (total_tokens as f64) * 0.000003 // Approximate cost
+        estimate_cost(input_tokens, output_tokens)
     }
     
     async fn create_message(
@@ -81,6 +81,21 @@ impl AnthropicProvider {
         model: Option<&str>,
         parameters: &AIParameters,
     ) -> AIResult<AnthropicResponse> {
+        let response = self.send_messages(messages, model, parameters, false).await?;
+        
+        response
+            .json::<AnthropicResponse>()
+            .await
+            .map_err(|e| AIError::AIProvider(format!("Failed to parse Anthropic response: {}", e)))
+    }
+    
+    async fn send_messages(
+        &self,
+        messages: Vec<AnthropicMessage>,
+        model: Option<&str>,
+        parameters: &AIParameters,
+        stream: bool,
+    ) -> AIResult<reqwest::Response> {
         let model = model.unwrap_or(&self.config.default_model);
         let base_url = self.config.base_url.as_deref().unwrap_or("https://api.anthropic.com");
         
@@ -91,6 +106,7 @@ impl AnthropicProvider {
             temperature: parameters.temperature,
             top_p: parameters.top_p,
             stop_sequences: parameters.stop_sequences.clone(),
+            stream,
         };
         
         let response = self
@@ -109,10 +125,7 @@ impl AnthropicProvider {
             return Err(AIError::AIProvider(format!("Anthropic API error: {}", error_text)));
         }
         
-        response
-            .json::<AnthropicResponse>()
-            .await
-            .map_err(|e| AIError::AIProvider(format!("Failed to parse Anthropic response: {}", e)))
+        Ok(response)
     }
 }
 
@@ -148,6 +161,63 @@ impl AIProvider for AnthropicProvider {
         })
     }
     
+    async fn generate_text_stream(&self, request: &TextGenerationRequest) -> AIResult<TextStream> {
+        let messages = vec![AnthropicMessage {
+            role: "user".to_string(),
+            content: request.prompt.clone(),
+        }];
+        
+        let response = self
+            .send_messages(messages, request.model.as_deref(), &request.parameters, true)
+            .await?;
+        
+        // Input tokens arrive with message_start, output tokens with the
+        // closing message_delta
+        let mut input_tokens = 0;
+        let chunks = sse_data(response).filter_map(move |data| {
+            let chunk = data.and_then(|data| {
+                let event: serde_json::Value = serde_json::from_str(&data)?;
+                let chunk = match event["type"].as_str() {
+                    Some("message_start") => {
+                        input_tokens = event["message"]["usage"]["input_tokens"].as_u64().unwrap_or(0) as u32;
+                        None
+                    }
+                    Some("content_block_delta") => event["delta"]["text"].as_str().map(|text| TextChunk {
+                        delta: text.to_string(),
+                        finish_reason: None,
+                        usage: None,
+                    }),
+                    Some("message_delta") => {
+                        let output_tokens = event["usage"]["output_tokens"].as_u64().unwrap_or(0) as u32;
+                        Some(TextChunk {
+                            delta: String::new(),
+                            finish_reason: Some(FinishReason::from_provider(
+                                event["delta"]["stop_reason"].as_str().unwrap_or("end_turn"),
+                            )),
+                            usage: Some(TokenUsage {
+                                prompt_tokens: input_tokens,
+                                completion_tokens: output_tokens,
+                                total_tokens: input_tokens + output_tokens,
+                                estimated_cost: estimate_cost(input_tokens, output_tokens),
+                            }),
+                        })
+                    }
+                    Some("error") => {
+                        return Err(AIError::AIProvider(format!(
+                            "Anthropic API error: {}",
+                            event["error"]["message"].as_str().unwrap_or("unknown error")
+                        )))
+                    }
+                    _ => None,
+                };
+                Ok(chunk)
+            });
+            futures::future::ready(chunk.transpose())
+        });
+        
+        Ok(Box::pin(chunks))
+    }
+    
     async fn classify_text(&self, request: &TextClassificationRequest) -> AIResult<TextClassificationResult> {
         let prompt = format!(
             "Classify the following text into one of these categories: {}\n\nText: {}\n\nRespond with only the category name.",
@@ -364,4 +434,11 @@ impl AIProvider for AnthropicProvider {
     fn get_provider_type(&self) -> crate::types::AIProvider {
         crate::types::AIProvider::Anthropic
     }
-}
\ No newline at end of file
+}
+
+pub(crate) fn estimate_cost(input_tokens: u32, output_tokens: u32) -> f64 {
+    // Anthropic pricing is typically different for input vs output tokens
+    // For simplicity, using average cost per token
+    let total_tokens = input_tokens + output_tokens;
+    (total_tokens as f64) * 0.000003 // Approximate cost
+}
@@ -0,0 +1,271 @@
+use crate::config::AzureOpenAIConfig;
+use crate::error::{AIError, AIResult};
+use crate::providers::{health_from_probe, sse_data, tasks, AIProvider, TextStream};
+use crate::types::*;
+use async_trait::async_trait;
+use futures::StreamExt;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Serialize)]
+struct AzureChatRequest {
+    messages: Vec<AzureMessage>,
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    frequency_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    presence_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "std::ops::Not::not")]
+    stream: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stream_options: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Serialize)]
+struct AzureMessage {
+    role: String,
+    content: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct AzureChatResponse {
+    choices: Vec<AzureChoice>,
+    usage: AzureUsage,
+}
+
+#[derive(Debug, Deserialize)]
+struct AzureChoice {
+    message: AzureResponseMessage,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AzureResponseMessage {
+    content: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct AzureUsage {
+    prompt_tokens: u32,
+    completion_tokens: u32,
+}
+
+/// OpenAI models served from an Azure OpenAI resource. Azure addresses
+/// models by deployment, so each model id this service exposes is mapped
+/// to the deployment serving it.
+pub struct AzureOpenAIProvider {
+    client: Client,
+    config: AzureOpenAIConfig,
+}
+
+impl AzureOpenAIProvider {
+    pub fn new(config: &AzureOpenAIConfig) -> Self {
+        let client = Client::new();
+
+        Self {
+            client,
+            config: config.clone(),
+        }
+    }
+
+    fn deployment(&self, model: Option<&str>) -> AIResult<&str> {
+        let model = model.unwrap_or(&self.config.default_model);
+        self.config
+            .deployments
+            .get(model)
+            .map(|deployment| deployment.as_str())
+            .ok_or_else(|| AIError::ModelNotAvailable(format!("No Azure OpenAI deployment for model {}", model)))
+    }
+
+    async fn complete(&self, prompt: &str, model: Option<&str>, parameters: &AIParameters) -> AIResult<(String, TokenUsage)> {
+        let response = self.send_chat(prompt, model, parameters, false).await?;
+
+        let response = response
+            .json::<AzureChatResponse>()
+            .await
+            .map_err(|e| AIError::AIProvider(format!("Failed to parse Azure OpenAI response: {}", e)))?;
+
+        let choice = response
+            .choices
+            .into_iter()
+            .next()
+            .ok_or_else(|| AIError::AIProvider("No response from Azure OpenAI".to_string()))?;
+
+        // Azure's content filter ends the generation without content
+        if choice.finish_reason.as_deref() == Some("content_filter") && choice.message.content.is_none() {
+            return Err(AIError::ContentFiltered("Azure OpenAI content filter blocked the response".to_string()));
+        }
+
+        let usage = TokenUsage {
+            prompt_tokens: response.usage.prompt_tokens,
+            completion_tokens: response.usage.completion_tokens,
+            total_tokens: response.usage.prompt_tokens + response.usage.completion_tokens,
+            estimated_cost: estimate_cost(response.usage.prompt_tokens, response.usage.completion_tokens),
+        };
+
+        Ok((choice.message.content.unwrap_or_default(), usage))
+    }
+
+    async fn send_chat(
+        &self,
+        prompt: &str,
+        model: Option<&str>,
+        parameters: &AIParameters,
+        stream: bool,
+    ) -> AIResult<reqwest::Response> {
+        let deployment = self.deployment(model)?;
+
+        let request = AzureChatRequest {
+            messages: vec![AzureMessage {
+                role: "user".to_string(),
+                content: prompt.to_string(),
+            }],
+            max_tokens: parameters.max_tokens.unwrap_or(self.config.max_tokens),
+            temperature: parameters.temperature,
+            top_p: parameters.top_p,
+            frequency_penalty: parameters.frequency_penalty,
+            presence_penalty: parameters.presence_penalty,
+            stop: parameters.stop_sequences.clone(),
+            stream,
+            // Usage is only reported while streaming when asked for
+            stream_options: stream.then(|| serde_json::json!({ "include_usage": true })),
+        };
+
+        let response = self
+            .client
+            .post(&format!(
+                "{}/openai/deployments/{}/chat/completions",
+                self.config.endpoint.trim_end_matches('/'),
+                deployment
+            ))
+            .query(&[("api-version", &self.config.api_version)])
+            .header("api-key", &self.config.api_key)
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| AIError::HttpClient(e))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(AIError::AIProvider(format!("Azure OpenAI API error: {}", error_text)));
+        }
+
+        Ok(response)
+    }
+}
+
+#[async_trait]
+impl AIProvider for AzureOpenAIProvider {
+    async fn generate_text(&self, request: &TextGenerationRequest) -> AIResult<TextGenerationResult> {
+        let (generated_text, usage) = self
+            .complete(&request.prompt, request.model.as_deref(), &request.parameters)
+            .await?;
+
+        Ok(TextGenerationResult {
+            generated_text,
+            usage,
+            quality_score: None,
+            metadata: HashMap::new(),
+        })
+    }
+
+    async fn generate_text_stream(&self, request: &TextGenerationRequest) -> AIResult<TextStream> {
+        let response = self
+            .send_chat(&request.prompt, request.model.as_deref(), &request.parameters, true)
+            .await?;
+
+        // The finish reason comes with the last choice chunk and usage in a
+        // final chunk without choices
+        let mut finish_reason = None;
+        let chunks = sse_data(response).filter_map(move |data| {
+            let chunk = data.and_then(|data| {
+                let event: serde_json::Value = serde_json::from_str(&data)?;
+
+                if let Some(choice) = event["choices"].get(0) {
+                    if let Some(reason) = choice["finish_reason"].as_str() {
+                        finish_reason = Some(FinishReason::from_provider(reason));
+                    }
+                    return Ok(choice["delta"]["content"].as_str().map(|text| TextChunk {
+                        delta: text.to_string(),
+                        finish_reason: None,
+                        usage: None,
+                    }));
+                }
+
+                let usage = &event["usage"];
+                if usage.is_null() {
+                    return Ok(None);
+                }
+                let prompt_tokens = usage["prompt_tokens"].as_u64().unwrap_or(0) as u32;
+                let completion_tokens = usage["completion_tokens"].as_u64().unwrap_or(0) as u32;
+                Ok(Some(TextChunk {
+                    delta: String::new(),
+                    finish_reason: Some(finish_reason.take().unwrap_or(FinishReason::Stop)),
+                    usage: Some(TokenUsage {
+                        prompt_tokens,
+                        completion_tokens,
+                        total_tokens: prompt_tokens + completion_tokens,
+                        estimated_cost: estimate_cost(prompt_tokens, completion_tokens),
+                    }),
+                }))
+            });
+            futures::future::ready(chunk.transpose())
+        });
+
+        Ok(Box::pin(chunks))
+    }
+
+    async fn classify_text(&self, request: &TextClassificationRequest) -> AIResult<TextClassificationResult> {
+        let (prompt, parameters) = tasks::classification_prompt(request);
+        let (response, usage) = self.complete(&prompt, request.model.as_deref(), &parameters).await?;
+        Ok(tasks::classification_result(request, &response, usage))
+    }
+
+    async fn summarize_text(&self, request: &TextSummarizationRequest) -> AIResult<TextSummarizationResult> {
+        let (prompt, parameters) = tasks::summarization_prompt(request);
+        let (summary, usage) = self.complete(&prompt, request.model.as_deref(), &parameters).await?;
+        Ok(tasks::summarization_result(request, summary, usage))
+    }
+
+    async fn extract_entities(&self, request: &EntityExtractionRequest) -> AIResult<EntityExtractionResult> {
+        let (prompt, parameters) = tasks::extraction_prompt(request);
+        let (response, usage) = self.complete(&prompt, request.model.as_deref(), &parameters).await?;
+        Ok(tasks::extraction_result(&response, usage))
+    }
+
+    async fn health_check(&self) -> AIResult<ProviderHealth> {
+        let start_time = std::time::Instant::now();
+
+        let parameters = AIParameters {
+            max_tokens: Some(5),
+            temperature: Some(0.0),
+            ..Default::default()
+        };
+
+        let result = self.complete("Hello", None, &parameters).await;
+        Ok(health_from_probe(start_time, result))
+    }
+
+    fn get_supported_models(&self) -> Vec<String> {
+        let mut models: Vec<String> = self.config.deployments.keys().cloned().collect();
+        models.sort();
+        models
+    }
+
+    fn get_provider_type(&self) -> crate::types::AIProvider {
+        crate::types::AIProvider::AzureOpenAI
+    }
+}
+
+fn estimate_cost(prompt_tokens: u32, completion_tokens: u32) -> f64 {
+    // Azure bills the OpenAI list prices; using an average cost per token
+    let total_tokens = prompt_tokens + completion_tokens;
+    (total_tokens as f64) * 0.000002 // Approximate cost
+}
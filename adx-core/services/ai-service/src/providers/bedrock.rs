@@ -0,0 +1,580 @@
+use crate::config::BedrockConfig;
+use crate::error::{AIError, AIResult};
+use crate::providers::{health_from_probe, tasks, AIProvider, TextStream};
+use crate::types::*;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
+use hmac::{Hmac, Mac};
+use reqwest::{Client, Method};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct ConverseRequest {
+    messages: Vec<ConverseMessage>,
+    inference_config: InferenceConfig,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ConverseMessage {
+    role: String,
+    content: Vec<ConverseContent>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct ConverseContent {
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct InferenceConfig {
+    max_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_sequences: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConverseResponse {
+    output: ConverseOutput,
+    stop_reason: String,
+    usage: ConverseUsage,
+}
+
+#[derive(Debug, Deserialize)]
+struct ConverseOutput {
+    message: ConverseMessage,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ConverseUsage {
+    input_tokens: u32,
+    output_tokens: u32,
+}
+
+/// Foundation models on AWS Bedrock, called through the model-independent
+/// Converse API with requests signed by AWS Signature Version 4
+pub struct BedrockProvider {
+    client: Client,
+    config: BedrockConfig,
+}
+
+impl BedrockProvider {
+    pub fn new(config: &BedrockConfig) -> Self {
+        let client = Client::new();
+
+        Self {
+            client,
+            config: config.clone(),
+        }
+    }
+
+    async fn complete(&self, prompt: &str, model: Option<&str>, parameters: &AIParameters) -> AIResult<(String, TokenUsage)> {
+        let response = self.send_converse(prompt, model, parameters, false).await?;
+
+        let response = response
+            .json::<ConverseResponse>()
+            .await
+            .map_err(|e| AIError::AIProvider(format!("Failed to parse Bedrock response: {}", e)))?;
+
+        if response.stop_reason == "guardrail_intervened" || response.stop_reason == "content_filtered" {
+            return Err(AIError::ContentFiltered("Bedrock guardrails blocked the response".to_string()));
+        }
+
+        let text = response
+            .output
+            .message
+            .content
+            .iter()
+            .map(|content| content.text.as_str())
+            .collect();
+
+        let usage = TokenUsage {
+            prompt_tokens: response.usage.input_tokens,
+            completion_tokens: response.usage.output_tokens,
+            total_tokens: response.usage.input_tokens + response.usage.output_tokens,
+            estimated_cost: estimate_cost(response.usage.input_tokens, response.usage.output_tokens),
+        };
+
+        Ok((text, usage))
+    }
+
+    async fn send_converse(
+        &self,
+        prompt: &str,
+        model: Option<&str>,
+        parameters: &AIParameters,
+        stream: bool,
+    ) -> AIResult<reqwest::Response> {
+        let model = model.unwrap_or(&self.config.default_model);
+
+        let request = ConverseRequest {
+            messages: vec![ConverseMessage {
+                role: "user".to_string(),
+                content: vec![ConverseContent {
+                    text: prompt.to_string(),
+                }],
+            }],
+            inference_config: InferenceConfig {
+                max_tokens: parameters.max_tokens.unwrap_or(self.config.max_tokens),
+                temperature: parameters.temperature,
+                top_p: parameters.top_p,
+                stop_sequences: parameters.stop_sequences.clone(),
+            },
+        };
+
+        // Model ids contain ':', which has to be escaped in the path
+        let path = format!(
+            "/model/{}/{}",
+            uri_encode(model),
+            if stream { "converse-stream" } else { "converse" }
+        );
+        let host = format!("bedrock-runtime.{}.amazonaws.com", self.config.region);
+
+        self.send_signed(Method::POST, &host, &path, serde_json::to_vec(&request)?).await
+    }
+
+    async fn send_signed(&self, method: Method, host: &str, path: &str, body: Vec<u8>) -> AIResult<reqwest::Response> {
+        // The signing name of both the bedrock and bedrock-runtime endpoints
+        let headers = sign_request(
+            &self.config,
+            "bedrock",
+            method.as_str(),
+            host,
+            path,
+            &body,
+            Utc::now(),
+        );
+
+        let mut builder = self
+            .client
+            .request(method, &format!("https://{}{}", host, path))
+            .header("Content-Type", "application/json");
+        for (name, value) in headers {
+            builder = builder.header(name, value);
+        }
+
+        let response = builder
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| AIError::HttpClient(e))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(AIError::AIProvider(format!("Bedrock API error: {}", error_text)));
+        }
+
+        Ok(response)
+    }
+
+    /// Foundation models in the region that generate text
+    pub async fn list_models(&self) -> AIResult<Vec<String>> {
+        let host = format!("bedrock.{}.amazonaws.com", self.config.region);
+        let response = self
+            .send_signed(Method::GET, &host, "/foundation-models", Vec::new())
+            .await?;
+
+        let body: serde_json::Value = response.json().await?;
+        let models = body["modelSummaries"]
+            .as_array()
+            .map(|models| {
+                models
+                    .iter()
+                    .filter(|model| {
+                        model["outputModalities"]
+                            .as_array()
+                            .is_some_and(|modalities| modalities.iter().any(|modality| modality == "TEXT"))
+                    })
+                    .filter_map(|model| model["modelId"].as_str())
+                    .map(|id| id.to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(models)
+    }
+}
+
+#[async_trait]
+impl AIProvider for BedrockProvider {
+    async fn generate_text(&self, request: &TextGenerationRequest) -> AIResult<TextGenerationResult> {
+        let (generated_text, usage) = self
+            .complete(&request.prompt, request.model.as_deref(), &request.parameters)
+            .await?;
+
+        Ok(TextGenerationResult {
+            generated_text,
+            usage,
+            quality_score: None,
+            metadata: HashMap::new(),
+        })
+    }
+
+    async fn generate_text_stream(&self, request: &TextGenerationRequest) -> AIResult<TextStream> {
+        let response = self
+            .send_converse(&request.prompt, request.model.as_deref(), &request.parameters, true)
+            .await?;
+
+        // The stop reason arrives with messageStop, usage with the metadata
+        // event after it
+        let mut stop_reason = None;
+        let chunks = event_stream(response).filter_map(move |event| {
+            let chunk = event.map(|(event_type, payload)| match event_type.as_str() {
+                "contentBlockDelta" => payload["delta"]["text"].as_str().map(|text| TextChunk {
+                    delta: text.to_string(),
+                    finish_reason: None,
+                    usage: None,
+                }),
+                "messageStop" => {
+                    stop_reason = payload["stopReason"].as_str().map(FinishReason::from_provider);
+                    None
+                }
+                "metadata" => {
+                    let input_tokens = payload["usage"]["inputTokens"].as_u64().unwrap_or(0) as u32;
+                    let output_tokens = payload["usage"]["outputTokens"].as_u64().unwrap_or(0) as u32;
+                    Some(TextChunk {
+                        delta: String::new(),
+                        finish_reason: Some(stop_reason.take().unwrap_or(FinishReason::Stop)),
+                        usage: Some(TokenUsage {
+                            prompt_tokens: input_tokens,
+                            completion_tokens: output_tokens,
+                            total_tokens: input_tokens + output_tokens,
+                            estimated_cost: estimate_cost(input_tokens, output_tokens),
+                        }),
+                    })
+                }
+                _ => None,
+            });
+            futures::future::ready(chunk.transpose())
+        });
+
+        Ok(Box::pin(chunks))
+    }
+
+    async fn classify_text(&self, request: &TextClassificationRequest) -> AIResult<TextClassificationResult> {
+        let (prompt, parameters) = tasks::classification_prompt(request);
+        let (response, usage) = self.complete(&prompt, request.model.as_deref(), &parameters).await?;
+        Ok(tasks::classification_result(request, &response, usage))
+    }
+
+    async fn summarize_text(&self, request: &TextSummarizationRequest) -> AIResult<TextSummarizationResult> {
+        let (prompt, parameters) = tasks::summarization_prompt(request);
+        let (summary, usage) = self.complete(&prompt, request.model.as_deref(), &parameters).await?;
+        Ok(tasks::summarization_result(request, summary, usage))
+    }
+
+    async fn extract_entities(&self, request: &EntityExtractionRequest) -> AIResult<EntityExtractionResult> {
+        let (prompt, parameters) = tasks::extraction_prompt(request);
+        let (response, usage) = self.complete(&prompt, request.model.as_deref(), &parameters).await?;
+        Ok(tasks::extraction_result(&response, usage))
+    }
+
+    async fn health_check(&self) -> AIResult<ProviderHealth> {
+        let start_time = std::time::Instant::now();
+
+        // Listing models checks the credentials without spending tokens
+        let result = self.list_models().await.and_then(|models| {
+            if models.contains(&self.config.default_model) {
+                Ok(())
+            } else {
+                Err(AIError::ModelNotAvailable(format!(
+                    "Bedrock model {} is not available in {}",
+                    self.config.default_model, self.config.region
+                )))
+            }
+        });
+        Ok(health_from_probe(start_time, result))
+    }
+
+    fn get_supported_models(&self) -> Vec<String> {
+        self.config.models.clone()
+    }
+
+    fn get_provider_type(&self) -> crate::types::AIProvider {
+        crate::types::AIProvider::Bedrock
+    }
+}
+
+fn estimate_cost(input_tokens: u32, output_tokens: u32) -> f64 {
+    // Bedrock pricing depends on the model; using an average cost per token
+    let total_tokens = input_tokens + output_tokens;
+    (total_tokens as f64) * 0.000003 // Approximate cost
+}
+
+/// Percent-encode everything but the characters SigV4 leaves unreserved
+fn uri_encode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
+
+/// Headers authenticating a request without a query string with AWS
+/// Signature Version 4. `path` is the path as sent, already escaped.
+fn sign_request(
+    config: &BedrockConfig,
+    service: &str,
+    method: &str,
+    host: &str,
+    path: &str,
+    body: &[u8],
+    now: DateTime<Utc>,
+) -> Vec<(&'static str, String)> {
+    let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+    let date = now.format("%Y%m%d").to_string();
+
+    let mut headers = vec![("host", host.to_string()), ("x-amz-date", amz_date.clone())];
+    if let Some(token) = config.session_token.as_ref().filter(|token| !token.is_empty()) {
+        headers.push(("x-amz-security-token", token.clone()));
+    }
+
+    // Services other than S3 escape each path segment a second time
+    let canonical_uri = path.split('/').map(uri_encode).collect::<Vec<_>>().join("/");
+    let canonical_headers: String = headers
+        .iter()
+        .map(|(name, value)| format!("{}:{}\n", name, value.trim()))
+        .collect();
+    let signed_headers = headers.iter().map(|(name, _)| *name).collect::<Vec<_>>().join(";");
+    let canonical_request = format!(
+        "{}\n{}\n\n{}\n{}\n{}",
+        method,
+        canonical_uri,
+        canonical_headers,
+        signed_headers,
+        hex::encode(Sha256::digest(body)),
+    );
+
+    let scope = format!("{}/{}/{}/aws4_request", date, config.region, service);
+    let string_to_sign = format!(
+        "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+        amz_date,
+        scope,
+        hex::encode(Sha256::digest(canonical_request.as_bytes())),
+    );
+
+    let key = hmac_sha256(format!("AWS4{}", config.secret_access_key).as_bytes(), &date);
+    let key = hmac_sha256(&key, &config.region);
+    let key = hmac_sha256(&key, service);
+    let key = hmac_sha256(&key, "aws4_request");
+    let signature = hex::encode(hmac_sha256(&key, &string_to_sign));
+
+    headers.push((
+        "authorization",
+        format!(
+            "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+            config.access_key_id, scope, signed_headers, signature
+        ),
+    ));
+    // reqwest sets the host header itself
+    headers.retain(|(name, _)| *name != "host");
+    headers
+}
+
+/// The event type and JSON payload of the messages of an AWS event stream
+/// response. Exception messages end the stream with an error.
+fn event_stream(response: reqwest::Response) -> impl Stream<Item = AIResult<(String, serde_json::Value)>> + Send {
+    let state = (response.bytes_stream().boxed(), Vec::new());
+
+    futures::stream::unfold(Some(state), |state| async move {
+        let (mut bytes, mut buffer) = state?;
+        loop {
+            match decode_message(&buffer) {
+                Ok(Some((message, len))) => {
+                    buffer.drain(..len);
+                    let payload: serde_json::Value = match serde_json::from_slice(&message.payload) {
+                        Ok(payload) => payload,
+                        Err(e) => return Some((Err(AIError::from(e)), None)),
+                    };
+                    if message.message_type == "exception" {
+                        let error = AIError::AIProvider(format!(
+                            "Bedrock {}: {}",
+                            message.event_type,
+                            payload["message"].as_str().unwrap_or("unknown error")
+                        ));
+                        return Some((Err(error), None));
+                    }
+                    return Some((Ok((message.event_type, payload)), Some((bytes, buffer))));
+                }
+                Ok(None) => {}
+                Err(e) => return Some((Err(e), None)),
+            }
+
+            match bytes.next().await {
+                Some(Ok(chunk)) => buffer.extend_from_slice(&chunk),
+                Some(Err(e)) => return Some((Err(AIError::HttpClient(e)), None)),
+                None => return None,
+            }
+        }
+    })
+}
+
+#[derive(Debug, PartialEq)]
+struct EventMessage {
+    /// `event` or `exception`
+    message_type: String,
+    /// The `:event-type`, or `:exception-type` of exceptions
+    event_type: String,
+    payload: Vec<u8>,
+}
+
+/// Decode the message at the start of the buffer, with the number of bytes
+/// it takes. `None` until the buffer holds a whole message.
+fn decode_message(buffer: &[u8]) -> AIResult<Option<(EventMessage, usize)>> {
+    // Prelude: total length, headers length, prelude CRC
+    if buffer.len() < 12 {
+        return Ok(None);
+    }
+    let total_len = u32::from_be_bytes([buffer[0], buffer[1], buffer[2], buffer[3]]) as usize;
+    let headers_len = u32::from_be_bytes([buffer[4], buffer[5], buffer[6], buffer[7]]) as usize;
+    if total_len < 16 + headers_len {
+        return Err(AIError::AIProvider("Malformed Bedrock event stream message".to_string()));
+    }
+    if buffer.len() < total_len {
+        return Ok(None);
+    }
+
+    let mut headers = HashMap::new();
+    let mut rest = &buffer[12..12 + headers_len];
+    while let Some((&name_len, tail)) = rest.split_first() {
+        let malformed = || AIError::AIProvider("Malformed Bedrock event stream header".to_string());
+        let name_len = name_len as usize;
+        let name = tail.get(..name_len).ok_or_else(malformed)?;
+        let (&value_type, tail) = tail[name_len..].split_first().ok_or_else(malformed)?;
+        let value_len = match value_type {
+            0 | 1 => 0,
+            2 => 1,
+            3 => 2,
+            4 => 4,
+            5 | 8 => 8,
+            9 => 16,
+            6 | 7 => {
+                let len = tail.get(..2).ok_or_else(malformed)?;
+                2 + u16::from_be_bytes([len[0], len[1]]) as usize
+            }
+            _ => return Err(malformed()),
+        };
+        let value = tail.get(..value_len).ok_or_else(malformed)?;
+        if value_type == 7 {
+            headers.insert(
+                String::from_utf8_lossy(name).into_owned(),
+                String::from_utf8_lossy(&value[2..]).into_owned(),
+            );
+        }
+        rest = &tail[value_len..];
+    }
+
+    let message_type = headers.remove(":message-type").unwrap_or_else(|| "event".to_string());
+    let event_type = if message_type == "exception" {
+        headers.remove(":exception-type")
+    } else {
+        headers.remove(":event-type")
+    }
+    .unwrap_or_default();
+
+    Ok(Some((
+        EventMessage {
+            message_type,
+            event_type,
+            payload: buffer[12 + headers_len..total_len - 4].to_vec(),
+        },
+        total_len,
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn encode_message(headers: &[(&str, &str)], payload: &[u8]) -> Vec<u8> {
+        let mut encoded_headers = Vec::new();
+        for (name, value) in headers {
+            encoded_headers.push(name.len() as u8);
+            encoded_headers.extend_from_slice(name.as_bytes());
+            encoded_headers.push(7);
+            encoded_headers.extend_from_slice(&(value.len() as u16).to_be_bytes());
+            encoded_headers.extend_from_slice(value.as_bytes());
+        }
+
+        let total_len = 16 + encoded_headers.len() + payload.len();
+        let mut message = Vec::new();
+        message.extend_from_slice(&(total_len as u32).to_be_bytes());
+        message.extend_from_slice(&(encoded_headers.len() as u32).to_be_bytes());
+        message.extend_from_slice(&[0; 4]);
+        message.extend_from_slice(&encoded_headers);
+        message.extend_from_slice(payload);
+        message.extend_from_slice(&[0; 4]);
+        message
+    }
+
+    #[test]
+    fn test_sign_request_matches_aws_example() {
+        // The get-vanilla case of the AWS Signature Version 4 test suite
+        let config = BedrockConfig {
+            region: "us-east-1".to_string(),
+            access_key_id: "AKIDEXAMPLE".to_string(),
+            secret_access_key: "wJalrXUtnFEMI/K7MDENG+bPxRfiCYEXAMPLEKEY".to_string(),
+            session_token: None,
+            default_model: String::new(),
+            models: Vec::new(),
+            max_tokens: 4096,
+        };
+        let now = Utc.with_ymd_and_hms(2015, 8, 30, 12, 36, 0).unwrap();
+
+        let headers = sign_request(&config, "service", "GET", "example.amazonaws.com", "/", b"", now);
+
+        assert_eq!(headers[0], ("x-amz-date", "20150830T123600Z".to_string()));
+        assert_eq!(
+            headers[1].1,
+            "AWS4-HMAC-SHA256 Credential=AKIDEXAMPLE/20150830/us-east-1/service/aws4_request, \
+             SignedHeaders=host;x-amz-date, \
+             Signature=5fa00fa31553b73ebf1942676e86291e8372ff2a2260956d9b8aae1d763fbf31"
+        );
+    }
+
+    #[test]
+    fn test_decode_message_waits_for_whole_message() {
+        let message = encode_message(
+            &[(":message-type", "event"), (":event-type", "contentBlockDelta")],
+            br#"{"delta":{"text":"Hi"}}"#,
+        );
+
+        assert_eq!(decode_message(&message[..message.len() - 1]).unwrap(), None);
+
+        let mut buffer = message.clone();
+        buffer.extend_from_slice(&message[..5]);
+        let (decoded, len) = decode_message(&buffer).unwrap().unwrap();
+        assert_eq!(len, message.len());
+        assert_eq!(decoded.message_type, "event");
+        assert_eq!(decoded.event_type, "contentBlockDelta");
+        assert_eq!(decoded.payload, br#"{"delta":{"text":"Hi"}}"#.to_vec());
+    }
+
+    #[test]
+    fn test_uri_encode_escapes_model_ids() {
+        assert_eq!(
+            uri_encode("anthropic.claude-3-haiku-20240307-v1:0"),
+            "anthropic.claude-3-haiku-20240307-v1%3A0"
+        );
+    }
+}
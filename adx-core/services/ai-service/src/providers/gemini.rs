@@ -0,0 +1,311 @@
+use crate::config::GeminiConfig;
+use crate::error::{AIError, AIResult};
+use crate::providers::{health_from_probe, sse_data, tasks, AIProvider, TextStream};
+use crate::types::*;
+use async_trait::async_trait;
+use futures::StreamExt;
+use reqwest::Client;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiRequest {
+    contents: Vec<GeminiContent>,
+    generation_config: GeminiGenerationConfig,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GeminiContent {
+    #[serde(default)]
+    role: String,
+    #[serde(default)]
+    parts: Vec<GeminiPart>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+struct GeminiPart {
+    #[serde(default)]
+    text: String,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiGenerationConfig {
+    max_output_tokens: u32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    temperature: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    top_p: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    stop_sequences: Option<Vec<String>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiResponse {
+    #[serde(default)]
+    candidates: Vec<GeminiCandidate>,
+    usage_metadata: Option<GeminiUsage>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiCandidate {
+    content: Option<GeminiContent>,
+    finish_reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GeminiUsage {
+    #[serde(default)]
+    prompt_token_count: u32,
+    #[serde(default)]
+    candidates_token_count: u32,
+}
+
+impl GeminiResponse {
+    fn text(&self) -> String {
+        self.candidates
+            .first()
+            .and_then(|candidate| candidate.content.as_ref())
+            .map(|content| content.parts.iter().map(|part| part.text.as_str()).collect())
+            .unwrap_or_default()
+    }
+
+    fn usage(&self) -> Option<TokenUsage> {
+        self.usage_metadata.as_ref().map(|usage| TokenUsage {
+            prompt_tokens: usage.prompt_token_count,
+            completion_tokens: usage.candidates_token_count,
+            total_tokens: usage.prompt_token_count + usage.candidates_token_count,
+            estimated_cost: estimate_cost(usage.prompt_token_count, usage.candidates_token_count),
+        })
+    }
+}
+
+pub struct GeminiProvider {
+    client: Client,
+    config: GeminiConfig,
+}
+
+impl GeminiProvider {
+    pub fn new(config: &GeminiConfig) -> Self {
+        let client = Client::new();
+
+        Self {
+            client,
+            config: config.clone(),
+        }
+    }
+
+    fn base_url(&self) -> &str {
+        self.config
+            .base_url
+            .as_deref()
+            .unwrap_or("https://generativelanguage.googleapis.com")
+            .trim_end_matches('/')
+    }
+
+    async fn complete(&self, prompt: &str, model: Option<&str>, parameters: &AIParameters) -> AIResult<(String, TokenUsage)> {
+        let response = self.send_generate(prompt, model, parameters, false).await?;
+
+        let response = response
+            .json::<GeminiResponse>()
+            .await
+            .map_err(|e| AIError::AIProvider(format!("Failed to parse Gemini response: {}", e)))?;
+
+        let candidate = response
+            .candidates
+            .first()
+            .ok_or_else(|| AIError::ContentFiltered("Gemini blocked the prompt".to_string()))?;
+        if candidate.finish_reason.as_deref() == Some("SAFETY") {
+            return Err(AIError::ContentFiltered("Gemini safety settings blocked the response".to_string()));
+        }
+
+        let usage = response
+            .usage()
+            .ok_or_else(|| AIError::AIProvider("No usage in Gemini response".to_string()))?;
+
+        Ok((response.text(), usage))
+    }
+
+    async fn send_generate(
+        &self,
+        prompt: &str,
+        model: Option<&str>,
+        parameters: &AIParameters,
+        stream: bool,
+    ) -> AIResult<reqwest::Response> {
+        let model = model.unwrap_or(&self.config.default_model);
+
+        let request = GeminiRequest {
+            contents: vec![GeminiContent {
+                role: "user".to_string(),
+                parts: vec![GeminiPart {
+                    text: prompt.to_string(),
+                }],
+            }],
+            generation_config: GeminiGenerationConfig {
+                max_output_tokens: parameters.max_tokens.unwrap_or(self.config.max_tokens),
+                temperature: parameters.temperature,
+                top_p: parameters.top_p,
+                stop_sequences: parameters.stop_sequences.clone(),
+            },
+        };
+
+        let mut builder = if stream {
+            self.client
+                .post(&format!("{}/v1beta/models/{}:streamGenerateContent", self.base_url(), model))
+                .query(&[("alt", "sse")])
+        } else {
+            self.client
+                .post(&format!("{}/v1beta/models/{}:generateContent", self.base_url(), model))
+        };
+        builder = builder.header("x-goog-api-key", &self.config.api_key);
+
+        let response = builder
+            .json(&request)
+            .send()
+            .await
+            .map_err(|e| AIError::HttpClient(e))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(AIError::AIProvider(format!("Gemini API error: {}", error_text)));
+        }
+
+        Ok(response)
+    }
+
+    /// Models available to the API key that can generate text
+    pub async fn list_models(&self) -> AIResult<Vec<String>> {
+        let response = self
+            .client
+            .get(&format!("{}/v1beta/models", self.base_url()))
+            .header("x-goog-api-key", &self.config.api_key)
+            .query(&[("pageSize", "1000")])
+            .send()
+            .await
+            .map_err(|e| AIError::HttpClient(e))?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+            return Err(AIError::AIProvider(format!("Gemini API error: {}", error_text)));
+        }
+
+        let body: serde_json::Value = response.json().await?;
+        let models = body["models"]
+            .as_array()
+            .map(|models| {
+                models
+                    .iter()
+                    .filter(|model| {
+                        model["supportedGenerationMethods"]
+                            .as_array()
+                            .is_some_and(|methods| methods.iter().any(|method| method == "generateContent"))
+                    })
+                    .filter_map(|model| model["name"].as_str())
+                    .map(|name| name.trim_start_matches("models/").to_string())
+                    .collect()
+            })
+            .unwrap_or_default();
+
+        Ok(models)
+    }
+}
+
+#[async_trait]
+impl AIProvider for GeminiProvider {
+    async fn generate_text(&self, request: &TextGenerationRequest) -> AIResult<TextGenerationResult> {
+        let (generated_text, usage) = self
+            .complete(&request.prompt, request.model.as_deref(), &request.parameters)
+            .await?;
+
+        Ok(TextGenerationResult {
+            generated_text,
+            usage,
+            quality_score: None,
+            metadata: HashMap::new(),
+        })
+    }
+
+    async fn generate_text_stream(&self, request: &TextGenerationRequest) -> AIResult<TextStream> {
+        let response = self
+            .send_generate(&request.prompt, request.model.as_deref(), &request.parameters, true)
+            .await?;
+
+        // Every event is a partial response; the one carrying the finish
+        // reason also carries the final usage
+        let chunks = sse_data(response).map(|data| {
+            let event: GeminiResponse = serde_json::from_str(&data?)?;
+            let finish_reason = event
+                .candidates
+                .first()
+                .and_then(|candidate| candidate.finish_reason.as_deref())
+                .map(FinishReason::from_provider);
+            let usage = finish_reason.as_ref().and_then(|_| event.usage());
+
+            Ok(TextChunk {
+                delta: event.text(),
+                finish_reason,
+                usage,
+            })
+        });
+
+        Ok(Box::pin(chunks))
+    }
+
+    async fn classify_text(&self, request: &TextClassificationRequest) -> AIResult<TextClassificationResult> {
+        let (prompt, parameters) = tasks::classification_prompt(request);
+        let (response, usage) = self.complete(&prompt, request.model.as_deref(), &parameters).await?;
+        Ok(tasks::classification_result(request, &response, usage))
+    }
+
+    async fn summarize_text(&self, request: &TextSummarizationRequest) -> AIResult<TextSummarizationResult> {
+        let (prompt, parameters) = tasks::summarization_prompt(request);
+        let (summary, usage) = self.complete(&prompt, request.model.as_deref(), &parameters).await?;
+        Ok(tasks::summarization_result(request, summary, usage))
+    }
+
+    async fn extract_entities(&self, request: &EntityExtractionRequest) -> AIResult<EntityExtractionResult> {
+        let (prompt, parameters) = tasks::extraction_prompt(request);
+        let (response, usage) = self.complete(&prompt, request.model.as_deref(), &parameters).await?;
+        Ok(tasks::extraction_result(&response, usage))
+    }
+
+    async fn health_check(&self) -> AIResult<ProviderHealth> {
+        let start_time = std::time::Instant::now();
+
+        // Listing models checks the key without spending tokens
+        let result = self.list_models().await.and_then(|models| {
+            if models.contains(&self.config.default_model) {
+                Ok(())
+            } else {
+                Err(AIError::ModelNotAvailable(format!(
+                    "Gemini model {} is not available",
+                    self.config.default_model
+                )))
+            }
+        });
+        Ok(health_from_probe(start_time, result))
+    }
+
+    fn get_supported_models(&self) -> Vec<String> {
+        vec![
+            "gemini-1.5-flash".to_string(),
+            "gemini-1.5-pro".to_string(),
+        ]
+    }
+
+    fn get_provider_type(&self) -> crate::types::AIProvider {
+        crate::types::AIProvider::Gemini
+    }
+}
+
+fn estimate_cost(prompt_tokens: u32, candidates_tokens: u32) -> f64 {
+    // Gemini pricing differs by model and prompt length; using an average
+    // cost per token
+    let total_tokens = prompt_tokens + candidates_tokens;
+    (total_tokens as f64) * 0.000001 // Approximate cost
+}
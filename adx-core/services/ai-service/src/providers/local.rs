@@ -1,52 +1,148 @@
 use crate::config::LocalAIConfig;
 use crate::error::{AIError, AIResult};
-use crate::providers::AIProvider;
+use crate::providers::{health_from_probe, AIProvider, TextStream};
 use crate::types::*;
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Utc};
+use futures::{Stream, StreamExt};
 use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
 
 #[derive(Debug, Serialize)]
-struct LocalAIRequest {
+struct OllamaGenerateRequest {
     model: String,
     prompt: String,
-    max_tokens: u32,
+    stream: bool,
+    options: OllamaOptions,
+    keep_alive: String,
+}
+
+/// Sampling and hardware options; unset options use the model's defaults
+#[derive(Debug, Serialize)]
+struct OllamaOptions {
+    num_predict: u32,
     temperature: f32,
     #[serde(skip_serializing_if = "Option::is_none")]
     top_p: Option<f32>,
     #[serde(skip_serializing_if = "Option::is_none")]
+    frequency_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    presence_penalty: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     stop: Option<Vec<String>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_gpu: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    main_gpu: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_thread: Option<u32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    num_ctx: Option<u32>,
 }
 
 #[derive(Debug, Deserialize)]
-struct LocalAIResponse {
-    id: String,
-    object: String,
-    created: u64,
-    model: String,
-    choices: Vec<LocalAIChoice>,
-    usage: LocalAIUsage,
+struct OllamaGenerateResponse {
+    #[serde(default)]
+    response: String,
+    done: bool,
+    done_reason: Option<String>,
+    #[serde(default)]
+    prompt_eval_count: u32,
+    #[serde(default)]
+    eval_count: u32,
+    error: Option<String>,
+}
+
+impl OllamaGenerateResponse {
+    fn usage(&self) -> TokenUsage {
+        TokenUsage {
+            prompt_tokens: self.prompt_eval_count,
+            completion_tokens: self.eval_count,
+            total_tokens: self.prompt_eval_count + self.eval_count,
+            estimated_cost: 0.0, // Local models have no cost
+        }
+    }
+}
+
+/// A model installed on the Ollama server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LocalModel {
+    pub name: String,
+    pub size: u64,
+    pub digest: String,
+    pub modified_at: DateTime<Utc>,
+    #[serde(default)]
+    pub details: LocalModelDetails,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LocalModelDetails {
+    pub family: Option<String>,
+    pub parameter_size: Option<String>,
+    pub quantization_level: Option<String>,
+}
+
+/// A model loaded into memory, with how much of it is on the GPU
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoadedModel {
+    pub name: String,
+    pub size: u64,
+    #[serde(default)]
+    pub size_vram: u64,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModelPullState {
+    Pulling,
+    Completed,
+    Failed,
+}
+
+/// Progress of downloading a model onto the Ollama server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModelPull {
+    pub model: String,
+    pub state: ModelPullState,
+    /// Ollama's description of the current step, e.g. "pulling manifest"
+    pub status: String,
+    pub completed_bytes: Option<u64>,
+    pub total_bytes: Option<u64>,
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
 }
 
 #[derive(Debug, Deserialize)]
-struct LocalAIChoice {
-    text: String,
-    index: u32,
-    finish_reason: Option<String>,
+struct OllamaTagsResponse {
+    #[serde(default)]
+    models: Vec<LocalModel>,
 }
 
 #[derive(Debug, Deserialize)]
-struct LocalAIUsage {
-    prompt_tokens: u32,
-    completion_tokens: u32,
-    total_tokens: u32,
+struct OllamaPsResponse {
+    #[serde(default)]
+    models: Vec<LoadedModel>,
 }
 
+#[derive(Debug, Deserialize)]
+struct OllamaPullProgress {
+    #[serde(default)]
+    status: String,
+    completed: Option<u64>,
+    total: Option<u64>,
+    error: Option<String>,
+}
+
+/// Models served by an Ollama server, or a llama.cpp server behind an
+/// Ollama-compatible API
 pub struct LocalAIProvider {
     client: Client,
     config: LocalAIConfig,
+    pulls: Arc<Mutex<HashMap<String, ModelPull>>>,
 }
 
 impl LocalAIProvider {
@@ -56,49 +152,249 @@ impl LocalAIProvider {
         Self {
             client,
             config: config.clone(),
+            pulls: Arc::new(Mutex::new(HashMap::new())),
         }
     }
     
-    async fn generate_completion(
-        &self,
-        prompt: &str,
-        model: Option<&str>,
-        parameters: &AIParameters,
-    ) -> AIResult<LocalAIResponse> {
+    fn base_url(&self) -> &str {
+        self.config.base_url.trim_end_matches('/')
+    }
+    
+    /// The Ollama tag of a model id
+    fn model_tag<'a>(&'a self, model: Option<&'a str>) -> &'a str {
         let model = model.unwrap_or_else(|| {
             self.config.models.first()
                 .map(|s| s.as_str())
                 .unwrap_or("llama2-7b")
         });
+        self.config.model_tags.get(model).map(|tag| tag.as_str()).unwrap_or(model)
+    }
+    
+    async fn generate_completion(
+        &self,
+        prompt: &str,
+        model: Option<&str>,
+        parameters: &AIParameters,
+    ) -> AIResult<OllamaGenerateResponse> {
+        let response = self.send_generate(prompt, model, parameters, false).await?;
+        
+        let response = response
+            .json::<OllamaGenerateResponse>()
+            .await
+            .map_err(|e| AIError::AIProvider(format!("Failed to parse Local AI response: {}", e)))?;
+        
+        match response.error {
+            Some(error) => Err(AIError::AIProvider(format!("Local AI error: {}", error))),
+            None => Ok(response),
+        }
+    }
+    
+    async fn send_generate(
+        &self,
+        prompt: &str,
+        model: Option<&str>,
+        parameters: &AIParameters,
+        stream: bool,
+    ) -> AIResult<reqwest::Response> {
+        let resources = &self.config.resources;
         
-        let request = LocalAIRequest {
-            model: model.to_string(),
+        let request = OllamaGenerateRequest {
+            model: self.model_tag(model).to_string(),
             prompt: prompt.to_string(),
-            max_tokens: parameters.max_tokens.unwrap_or(1000),
-            temperature: parameters.temperature.unwrap_or(0.7),
-            top_p: parameters.top_p,
-            stop: parameters.stop_sequences.clone(),
+            stream,
+            options: OllamaOptions {
+                num_predict: parameters.max_tokens.unwrap_or(1000),
+                temperature: parameters.temperature.unwrap_or(0.7),
+                top_p: parameters.top_p,
+                frequency_penalty: parameters.frequency_penalty,
+                presence_penalty: parameters.presence_penalty,
+                stop: parameters.stop_sequences.clone(),
+                num_gpu: resources.num_gpu,
+                main_gpu: resources.main_gpu,
+                num_thread: resources.num_thread,
+                num_ctx: resources.num_ctx,
+            },
+            keep_alive: resources.keep_alive.clone(),
         };
         
         let response = self
             .client
-            .post(&format!("{}/v1/completions", self.config.base_url))
-            .header("Content-Type", "application/json")
+            .post(&format!("{}/api/generate", self.base_url()))
             .json(&request)
             .send()
             .await
             .map_err(|e| AIError::HttpClient(e))?;
         
-        if !response.status().is_success() {
-            let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
-            return Err(AIError::AIProvider(format!("Local AI error: {}", error_text)));
+        check_response(response).await
+    }
+    
+    /// Models installed on the server
+    pub async fn list_models(&self) -> AIResult<Vec<LocalModel>> {
+        let response = self
+            .client
+            .get(&format!("{}/api/tags", self.base_url()))
+            .send()
+            .await?;
+        
+        let tags: OllamaTagsResponse = check_response(response).await?.json().await?;
+        Ok(tags.models)
+    }
+    
+    /// Models currently loaded into memory
+    pub async fn loaded_models(&self) -> AIResult<Vec<LoadedModel>> {
+        let response = self
+            .client
+            .get(&format!("{}/api/ps", self.base_url()))
+            .send()
+            .await?;
+        
+        let loaded: OllamaPsResponse = check_response(response).await?.json().await?;
+        Ok(loaded.models)
+    }
+    
+    /// Start downloading a model, or return the download already running.
+    /// The download runs in the background; its progress is reported by
+    /// `pulls`.
+    pub fn pull_model(&self, model: &str) -> ModelPull {
+        let tag = self.model_tag(Some(model)).to_string();
+        
+        let mut pulls = self.pulls.lock().unwrap();
+        if let Some(pull) = pulls.get(&tag) {
+            if pull.state == ModelPullState::Pulling {
+                return pull.clone();
+            }
         }
         
-        response
-            .json::<LocalAIResponse>()
-            .await
-            .map_err(|e| AIError::AIProvider(format!("Failed to parse Local AI response: {}", e)))
+        let pull = ModelPull {
+            model: tag.clone(),
+            state: ModelPullState::Pulling,
+            status: "starting".to_string(),
+            completed_bytes: None,
+            total_bytes: None,
+            error: None,
+            started_at: Utc::now(),
+            finished_at: None,
+        };
+        pulls.insert(tag.clone(), pull.clone());
+        drop(pulls);
+        
+        let client = self.client.clone();
+        let url = format!("{}/api/pull", self.base_url());
+        let pulls = self.pulls.clone();
+        tokio::spawn(async move {
+            let result = run_pull(&client, &url, &tag, &pulls).await;
+            
+            let mut pulls = pulls.lock().unwrap();
+            if let Some(pull) = pulls.get_mut(&tag) {
+                pull.finished_at = Some(Utc::now());
+                match result {
+                    Ok(()) => pull.state = ModelPullState::Completed,
+                    Err(e) => {
+                        tracing::warn!("Pulling local model {} failed: {}", tag, e);
+                        pull.state = ModelPullState::Failed;
+                        pull.error = Some(e.to_string());
+                    }
+                }
+            }
+        });
+        
+        pull
+    }
+    
+    /// Downloads started since the service started, most recent first
+    pub fn pulls(&self) -> Vec<ModelPull> {
+        let mut pulls: Vec<ModelPull> = self.pulls.lock().unwrap().values().cloned().collect();
+        pulls.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        pulls
     }
+    
+    pub async fn delete_model(&self, model: &str) -> AIResult<()> {
+        let response = self
+            .client
+            .delete(&format!("{}/api/delete", self.base_url()))
+            .json(&serde_json::json!({ "model": self.model_tag(Some(model)) }))
+            .send()
+            .await?;
+        
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(AIError::NotFound(format!("Model {} is not installed", model)));
+        }
+        check_response(response).await?;
+        Ok(())
+    }
+}
+
+async fn check_response(response: reqwest::Response) -> AIResult<reqwest::Response> {
+    if !response.status().is_success() {
+        let error_text = response.text().await.unwrap_or_else(|_| "Unknown error".to_string());
+        return Err(AIError::AIProvider(format!("Local AI error: {}", error_text)));
+    }
+    Ok(response)
+}
+
+async fn run_pull(
+    client: &Client,
+    url: &str,
+    tag: &str,
+    pulls: &Mutex<HashMap<String, ModelPull>>,
+) -> AIResult<()> {
+    let response = client
+        .post(url)
+        .json(&serde_json::json!({ "model": tag, "stream": true }))
+        .send()
+        .await?;
+    let mut progress = Box::pin(json_lines::<OllamaPullProgress>(check_response(response).await?));
+    
+    while let Some(update) = progress.next().await {
+        let update = update?;
+        if let Some(error) = update.error {
+            return Err(AIError::AIProvider(error));
+        }
+        if let Some(pull) = pulls.lock().unwrap().get_mut(tag) {
+            pull.status = update.status;
+            pull.completed_bytes = update.completed.or(pull.completed_bytes);
+            pull.total_bytes = update.total.or(pull.total_bytes);
+        }
+    }
+    
+    let status = pulls.lock().unwrap().get(tag).map(|pull| pull.status.clone());
+    if status.as_deref() != Some("success") {
+        return Err(AIError::AIProvider(format!("Pull of {} ended before completing", tag)));
+    }
+    Ok(())
+}
+
+/// The objects of a newline-delimited JSON response, as Ollama streams them
+fn json_lines<T>(response: reqwest::Response) -> impl Stream<Item = AIResult<T>> + Send
+where
+    T: serde::de::DeserializeOwned + Send + 'static,
+{
+    let state = (response.bytes_stream().boxed(), Vec::new());
+    
+    futures::stream::unfold(Some(state), |state| async move {
+        let (mut bytes, mut buffer) = state?;
+        loop {
+            if let Some(end) = buffer.iter().position(|byte| *byte == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                if line.iter().all(|byte| byte.is_ascii_whitespace()) {
+                    continue;
+                }
+                let item = serde_json::from_slice(&line).map_err(AIError::from);
+                return Some((item, Some((bytes, buffer))));
+            }
+            
+            match bytes.next().await {
+                Some(Ok(chunk)) => buffer.extend_from_slice(&chunk),
+                Some(Err(e)) => return Some((Err(AIError::HttpClient(e)), None)),
+                None if buffer.iter().all(|byte| byte.is_ascii_whitespace()) => return None,
+                // The last object may not end with a newline
+                None => {
+                    let item = serde_json::from_slice(&buffer).map_err(AIError::from);
+                    return Some((item, None));
+                }
+            }
+        }
+    })
 }
 
 #[async_trait]
@@ -108,26 +404,45 @@ impl AIProvider for LocalAIProvider {
             .generate_completion(&request.prompt, request.model.as_deref(), &request.parameters)
             .await?;
         
-        let choice = response
-            .choices
-            .first()
-            .ok_or_else(|| AIError::AIProvider("No response from Local AI".to_string()))?;
-        
-        let usage = TokenUsage {
-            prompt_tokens: response.usage.prompt_tokens,
-            completion_tokens: response.usage.completion_tokens,
-            total_tokens: response.usage.total_tokens,
-            estimated_cost: 0.0, // Local models have no cost
-        };
-        
         Ok(TextGenerationResult {
-            generated_text: choice.text.clone(),
-            usage,
+            generated_text: response.response.clone(),
+            usage: response.usage(),
             quality_score: None,
             metadata: HashMap::new(),
         })
     }
     
+    async fn generate_text_stream(&self, request: &TextGenerationRequest) -> AIResult<TextStream> {
+        let response = self
+            .send_generate(&request.prompt, request.model.as_deref(), &request.parameters, true)
+            .await?;
+        
+        // The last object has `done` set and carries the token counts
+        let chunks = json_lines::<OllamaGenerateResponse>(response).map(|event| {
+            let event = event?;
+            if let Some(error) = event.error {
+                return Err(AIError::AIProvider(format!("Local AI error: {}", error)));
+            }
+            
+            let (finish_reason, usage) = if event.done {
+                (
+                    Some(FinishReason::from_provider(event.done_reason.as_deref().unwrap_or("stop"))),
+                    Some(event.usage()),
+                )
+            } else {
+                (None, None)
+            };
+            
+            Ok(TextChunk {
+                delta: event.response,
+                finish_reason,
+                usage,
+            })
+        });
+        
+        Ok(Box::pin(chunks))
+    }
+    
     async fn classify_text(&self, request: &TextClassificationRequest) -> AIResult<TextClassificationResult> {
         let prompt = format!(
             "Classify the following text into one of these categories: {}\n\nText: {}\n\nCategory:",
@@ -145,12 +460,7 @@ impl AIProvider for LocalAIProvider {
             .generate_completion(&prompt, request.model.as_deref(), &parameters)
             .await?;
         
-        let choice = response
-            .choices
-            .first()
-            .ok_or_else(|| AIError::AIProvider("No response from Local AI".to_string()))?;
-        
-        let result_text = choice.text.trim();
+        let result_text = response.response.trim();
         
         // Find the best matching category
         let category = request
@@ -160,12 +470,7 @@ impl AIProvider for LocalAIProvider {
             .unwrap_or(&request.categories[0])
             .clone();
         
-        let usage = TokenUsage {
-            prompt_tokens: response.usage.prompt_tokens,
-            completion_tokens: response.usage.completion_tokens,
-            total_tokens: response.usage.total_tokens,
-            estimated_cost: 0.0,
-        };
+        let usage = response.usage();
         
         // Create confidence scores (simplified)
         let mut all_scores = HashMap::new();
@@ -206,19 +511,9 @@ impl AIProvider for LocalAIProvider {
             .generate_completion(&prompt, request.model.as_deref(), &parameters)
             .await?;
         
-        let choice = response
-            .choices
-            .first()
-            .ok_or_else(|| AIError::AIProvider("No response from Local AI".to_string()))?;
+        let summary = &response.response;
         
-        let summary = &choice.text;
-        
-        let usage = TokenUsage {
-            prompt_tokens: response.usage.prompt_tokens,
-            completion_tokens: response.usage.completion_tokens,
-            total_tokens: response.usage.total_tokens,
-            estimated_cost: 0.0,
-        };
+        let usage = response.usage();
         
         // Extract key points (simplified)
         let key_points: Vec<String> = summary
@@ -261,14 +556,9 @@ impl AIProvider for LocalAIProvider {
             .generate_completion(&prompt, request.model.as_deref(), &parameters)
             .await?;
         
-        let choice = response
-            .choices
-            .first()
-            .ok_or_else(|| AIError::AIProvider("No response from Local AI".to_string()))?;
-        
         // Parse simple text response (not JSON for local models)
-        let entities: Vec<ExtractedEntity> = choice
-            .text
+        let entities: Vec<ExtractedEntity> = response
+            .response
             .lines()
             .filter_map(|line| {
                 let parts: Vec<&str> = line.split(':').collect();
@@ -304,12 +594,7 @@ impl AIProvider for LocalAIProvider {
             })
             .collect();
         
-        let usage = TokenUsage {
-            prompt_tokens: response.usage.prompt_tokens,
-            completion_tokens: response.usage.completion_tokens,
-            total_tokens: response.usage.total_tokens,
-            estimated_cost: 0.0,
-        };
+        let usage = response.usage();
         
         Ok(EntityExtractionResult {
             entities,
@@ -320,31 +605,23 @@ impl AIProvider for LocalAIProvider {
     async fn health_check(&self) -> AIResult<ProviderHealth> {
         let start_time = std::time::Instant::now();
         
-        let parameters = AIParameters {
-            max_tokens: Some(5),
-            temperature: Some(0.0),
-            ..Default::default()
+        // Listing models checks the server is up without loading a model
+        let installed = match self.list_models().await {
+            Ok(installed) => installed,
+            Err(e) => return Ok(health_from_probe(start_time, Err::<(), _>(e))),
         };
         
-        match self.generate_completion("Hello", None, &parameters).await {
-            Ok(_) => {
-                let response_time = start_time.elapsed().as_millis() as u64;
-                Ok(ProviderHealth {
-                    status: HealthStatus::Healthy,
-                    response_time_ms: Some(response_time),
-                    error_rate: 0.0,
-                    last_error: None,
-                    last_check: Utc::now(),
-                })
-            }
-            Err(e) => Ok(ProviderHealth {
-                status: HealthStatus::Unhealthy,
-                response_time_ms: None,
-                error_rate: 1.0,
-                last_error: Some(e.to_string()),
-                last_check: Utc::now(),
-            }),
+        let mut health = health_from_probe(start_time, Ok(()));
+        let missing: Vec<&str> = self.config.models
+            .iter()
+            .map(|model| self.model_tag(Some(model)))
+            .filter(|tag| !installed.iter().any(|model| model.name == *tag || model.name == format!("{}:latest", tag)))
+            .collect();
+        if !missing.is_empty() {
+            health.status = HealthStatus::Degraded;
+            health.last_error = Some(format!("Models not installed: {}", missing.join(", ")));
         }
+        Ok(health)
     }
     
     fn get_supported_models(&self) -> Vec<String> {
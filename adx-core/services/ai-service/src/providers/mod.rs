@@ -1,14 +1,24 @@
 pub mod openai;
 pub mod anthropic;
 pub mod local;
+pub mod azure_openai;
+pub mod bedrock;
+pub mod gemini;
+mod tasks;
 
 use crate::error::{AIError, AIResult};
 use crate::types::*;
 use async_trait::async_trait;
+use futures::{Stream, StreamExt};
+use std::pin::Pin;
+
+/// Chunks of a generation in the order the provider produced them
+pub type TextStream = Pin<Box<dyn Stream<Item = AIResult<TextChunk>> + Send>>;
 
 #[async_trait]
 pub trait AIProvider: Send + Sync {
     async fn generate_text(&self, request: &TextGenerationRequest) -> AIResult<TextGenerationResult>;
+    async fn generate_text_stream(&self, request: &TextGenerationRequest) -> AIResult<TextStream>;
     async fn classify_text(&self, request: &TextClassificationRequest) -> AIResult<TextClassificationResult>;
     async fn summarize_text(&self, request: &TextSummarizationRequest) -> AIResult<TextSummarizationResult>;
     async fn extract_entities(&self, request: &EntityExtractionRequest) -> AIResult<EntityExtractionResult>;
@@ -21,6 +31,9 @@ pub struct AIProviderManager {
     openai: Option<openai::OpenAIProvider>,
     anthropic: Option<anthropic::AnthropicProvider>,
     local: Option<local::LocalAIProvider>,
+    azure_openai: Option<azure_openai::AzureOpenAIProvider>,
+    bedrock: Option<bedrock::BedrockProvider>,
+    gemini: Option<gemini::GeminiProvider>,
 }
 
 impl AIProviderManager {
@@ -43,10 +56,31 @@ impl AIProviderManager {
             None
         };
         
+        let azure_openai = if !config.azure_openai.api_key.is_empty() && !config.azure_openai.endpoint.is_empty() {
+            Some(azure_openai::AzureOpenAIProvider::new(&config.azure_openai))
+        } else {
+            None
+        };
+        
+        let bedrock = if !config.bedrock.access_key_id.is_empty() {
+            Some(bedrock::BedrockProvider::new(&config.bedrock))
+        } else {
+            None
+        };
+        
+        let gemini = if !config.gemini.api_key.is_empty() {
+            Some(gemini::GeminiProvider::new(&config.gemini))
+        } else {
+            None
+        };
+        
         Self {
             openai,
             anthropic,
             local,
+            azure_openai,
+            bedrock,
+            gemini,
         }
     }
     
@@ -67,63 +101,110 @@ impl AIProviderManager {
                     .map(|p| p as &dyn AIProvider)
                     .ok_or_else(|| AIError::AIProvider("Local AI provider not configured".to_string()))
             }
+            crate::types::AIProvider::AzureOpenAI => {
+                self.azure_openai.as_ref()
+                    .map(|p| p as &dyn AIProvider)
+                    .ok_or_else(|| AIError::AIProvider("Azure OpenAI provider not configured".to_string()))
+            }
+            crate::types::AIProvider::Bedrock => {
+                self.bedrock.as_ref()
+                    .map(|p| p as &dyn AIProvider)
+                    .ok_or_else(|| AIError::AIProvider("Bedrock provider not configured".to_string()))
+            }
+            crate::types::AIProvider::Gemini => {
+                self.gemini.as_ref()
+                    .map(|p| p as &dyn AIProvider)
+                    .ok_or_else(|| AIError::AIProvider("Gemini provider not configured".to_string()))
+            }
         }
     }
     
+    /// The local provider, for managing the models it serves
+    pub fn local(&self) -> AIResult<&local::LocalAIProvider> {
+        self.local.as_ref()
+            .ok_or_else(|| AIError::AIProvider("Local AI provider not configured".to_string()))
+    }
+    
+    /// The providers with configuration to run
+    pub fn configured_providers(&self) -> Vec<&dyn AIProvider> {
+        let providers: [Option<&dyn AIProvider>; 6] = [
+            self.openai.as_ref().map(|p| p as &dyn AIProvider),
+            self.anthropic.as_ref().map(|p| p as &dyn AIProvider),
+            self.local.as_ref().map(|p| p as &dyn AIProvider),
+            self.azure_openai.as_ref().map(|p| p as &dyn AIProvider),
+            self.bedrock.as_ref().map(|p| p as &dyn AIProvider),
+            self.gemini.as_ref().map(|p| p as &dyn AIProvider),
+        ];
+        providers.into_iter().flatten().collect()
+    }
+    
     pub async fn health_check_all(&self) -> AIResult<std::collections::HashMap<crate::types::AIProvider, ProviderHealth>> {
         let mut health_results = std::collections::HashMap::new();
         
-        if let Some(openai) = &self.openai {
-            match openai.health_check().await {
-                Ok(health) => {
-                    health_results.insert(crate::types::AIProvider::OpenAI, health);
-                }
-                Err(e) => {
-                    health_results.insert(crate::types::AIProvider::OpenAI, ProviderHealth {
-                        status: HealthStatus::Unhealthy,
-                        response_time_ms: None,
-                        error_rate: 1.0,
-                        last_error: Some(e.to_string()),
-                        last_check: chrono::Utc::now(),
-                    });
-                }
-            }
+        for provider in self.configured_providers() {
+            let health = match provider.health_check().await {
+                Ok(health) => health,
+                Err(e) => ProviderHealth {
+                    status: HealthStatus::Unhealthy,
+                    response_time_ms: None,
+                    error_rate: 1.0,
+                    last_error: Some(e.to_string()),
+                    last_check: chrono::Utc::now(),
+                },
+            };
+            health_results.insert(provider.get_provider_type(), health);
         }
         
-        if let Some(anthropic) = &self.anthropic {
-            match anthropic.health_check().await {
-                Ok(health) => {
-                    health_results.insert(crate::types::AIProvider::Anthropic, health);
-                }
-                Err(e) => {
-                    health_results.insert(crate::types::AIProvider::Anthropic, ProviderHealth {
-                        status: HealthStatus::Unhealthy,
-                        response_time_ms: None,
-                        error_rate: 1.0,
-                        last_error: Some(e.to_string()),
-                        last_check: chrono::Utc::now(),
-                    });
+        Ok(health_results)
+    }
+}
+
+/// Health from the outcome of a provider's health probe
+pub(crate) fn health_from_probe<T>(start_time: std::time::Instant, result: AIResult<T>) -> ProviderHealth {
+    match result {
+        Ok(_) => ProviderHealth {
+            status: HealthStatus::Healthy,
+            response_time_ms: Some(start_time.elapsed().as_millis() as u64),
+            error_rate: 0.0,
+            last_error: None,
+            last_check: chrono::Utc::now(),
+        },
+        Err(e) => ProviderHealth {
+            status: HealthStatus::Unhealthy,
+            response_time_ms: None,
+            error_rate: 1.0,
+            last_error: Some(e.to_string()),
+            last_check: chrono::Utc::now(),
+        },
+    }
+}
+
+/// Payloads of the `data:` lines of a server-sent event response, ending
+/// at the OpenAI-style `[DONE]` marker or when the response ends
+pub(crate) fn sse_data(response: reqwest::Response) -> impl Stream<Item = AIResult<String>> + Send {
+    let state = (response.bytes_stream().boxed(), Vec::new());
+
+    futures::stream::unfold(Some(state), |state| async move {
+        let (mut bytes, mut buffer) = state?;
+        loop {
+            if let Some(end) = buffer.iter().position(|byte| *byte == b'\n') {
+                let line: Vec<u8> = buffer.drain(..=end).collect();
+                let line = String::from_utf8_lossy(&line);
+                let Some(data) = line.trim_end().strip_prefix("data:") else {
+                    continue;
+                };
+                let data = data.trim_start();
+                if data == "[DONE]" {
+                    return None;
                 }
+                return Some((Ok(data.to_string()), Some((bytes, buffer))));
             }
-        }
-        
-        if let Some(local) = &self.local {
-            match local.health_check().await {
-                Ok(health) => {
-                    health_results.insert(crate::types::AIProvider::Local, health);
-                }
-                Err(e) => {
-                    health_results.insert(crate::types::AIProvider::Local, ProviderHealth {
-                        status: HealthStatus::Unhealthy,
-                        response_time_ms: None,
-                        error_rate: 1.0,
-                        last_error: Some(e.to_string()),
-                        last_check: chrono::Utc::now(),
-                    });
-                }
+
+            match bytes.next().await {
+                Some(Ok(chunk)) => buffer.extend_from_slice(&chunk),
+                Some(Err(e)) => return Some((Err(AIError::HttpClient(e)), None)),
+                None => return None,
             }
         }
-        
-        Ok(health_results)
-    }
-}
\ No newline at end of file
+    })
+}
@@ -1,6 +1,6 @@
 use crate::config::OpenAIConfig;
 use crate::error::{AIError, AIResult};
-use crate::providers::AIProvider;
+use crate::providers::{AIProvider, TextStream};
 use crate::types::*;
 use async_openai::{
     types::{
@@ -11,13 +11,15 @@ use async_openai::{
 };
 use async_trait::async_trait;
 use chrono::Utc;
+use futures::StreamExt;
 use std::collections::HashMap;
+use std::sync::Arc;
 use tiktoken_rs::tiktoken::{get_bpe_from_model, CoreBPE};
 
 pub struct OpenAIProvider {
     client: Client<async_openai::config::OpenAIConfig>,
     config: OpenAIConfig,
-    tokenizer: Option<CoreBPE>,
+    tokenizer: Option<Arc<CoreBPE>>,
 }
 
 impl OpenAIProvider {
@@ -32,7 +34,7 @@ impl OpenAIProvider {
         let client = Client::with_config(openai_config);
         
         // Initialize tokenizer for the default model
-        let tokenizer = get_bpe_from_model(&config.default_model).ok();
+        let tokenizer = get_bpe_from_model(&config.default_model).ok().map(Arc::new);
         
         Self {
             client,
@@ -42,12 +44,7 @@ impl OpenAIProvider {
     }
     
     fn count_tokens(&self, text: &str) -> u32 {
-        if let Some(tokenizer) = &self.tokenizer {
-            tokenizer.encode_with_special_tokens(text).len() as u32
-        } else {
-            // Fallback estimation: ~4 characters per token
-            (text.len() / 4) as u32
-        }
+        count_tokens(self.tokenizer.as_deref(), text)
     }
     
     fn calculate_cost(&self, prompt_tokens: u32, completion_tokens: u32) -> f64 {
@@ -55,15 +52,15 @@ impl OpenAIProvider {
         (total_tokens as f64) * self.config.cost_per_token
     }
     
-    async fn create_chat_completion(
+    fn chat_request(
         &self,
         messages: Vec<ChatCompletionRequestMessage>,
         model: Option<&str>,
         parameters: &AIParameters,
-    ) -> AIResult<async_openai::types::CreateChatCompletionResponse> {
+    ) -> CreateChatCompletionRequest {
         let model = model.unwrap_or(&self.config.default_model);
         
-        let request = CreateChatCompletionRequest {
+        CreateChatCompletionRequest {
             model: model.to_string(),
             messages,
             max_tokens: parameters.max_tokens.or(Some(self.config.max_tokens)),
@@ -73,7 +70,16 @@ impl OpenAIProvider {
             presence_penalty: parameters.presence_penalty,
             stop: parameters.stop_sequences.clone(),
             ..Default::default()
-        };
+        }
+    }
+    
+    async fn create_chat_completion(
+        &self,
+        messages: Vec<ChatCompletionRequestMessage>,
+        model: Option<&str>,
+        parameters: &AIParameters,
+    ) -> AIResult<async_openai::types::CreateChatCompletionResponse> {
+        let request = self.chat_request(messages, model, parameters);
         
         self.client
             .chat()
@@ -128,6 +134,57 @@ impl AIProvider for OpenAIProvider {
         })
     }
     
+    async fn generate_text_stream(&self, request: &TextGenerationRequest) -> AIResult<TextStream> {
+        let messages = vec![ChatCompletionRequestMessage::User(
+            ChatCompletionRequestUserMessage {
+                content: async_openai::types::ChatCompletionRequestUserMessageContent::Text(
+                    request.prompt.clone(),
+                ),
+                name: None,
+            },
+        )];
+        
+        let chat_request = self.chat_request(messages, request.model.as_deref(), &request.parameters);
+        let stream = self
+            .client
+            .chat()
+            .create_stream(chat_request)
+            .await
+            .map_err(|e| AIError::AIProvider(format!("OpenAI API error: {}", e)))?;
+        
+        // Streamed completions carry no usage, so it is counted from the text
+        let prompt_tokens = self.count_tokens(&request.prompt);
+        let tokenizer = self.tokenizer.clone();
+        let cost_per_token = self.config.cost_per_token;
+        let mut generated = String::new();
+        
+        Ok(Box::pin(stream.map(move |response| {
+            let response = response.map_err(|e| AIError::AIProvider(format!("OpenAI API error: {}", e)))?;
+            let choice = response.choices.first();
+            let delta = choice.and_then(|c| c.delta.content.clone()).unwrap_or_default();
+            generated.push_str(&delta);
+            
+            let finish_reason = choice
+                .and_then(|c| serde_json::to_value(&c.finish_reason).ok())
+                .and_then(|reason| reason.as_str().map(FinishReason::from_provider));
+            let usage = finish_reason.as_ref().map(|_| {
+                let completion_tokens = count_tokens(tokenizer.as_deref(), &generated);
+                TokenUsage {
+                    prompt_tokens,
+                    completion_tokens,
+                    total_tokens: prompt_tokens + completion_tokens,
+                    estimated_cost: (prompt_tokens + completion_tokens) as f64 * cost_per_token,
+                }
+            });
+            
+            Ok(TextChunk {
+                delta,
+                finish_reason,
+                usage,
+            })
+        })))
+    }
+    
     async fn classify_text(&self, request: &TextClassificationRequest) -> AIResult<TextClassificationResult> {
         let prompt = format!(
             "Classify the following text into one of these categories: {}\n\nText: {}\n\nCategory:",
@@ -394,6 +451,15 @@ impl AIProvider for OpenAIProvider {
     }
 }
 
+fn count_tokens(tokenizer: Option<&CoreBPE>, text: &str) -> u32 {
+    if let Some(tokenizer) = tokenizer {
+        tokenizer.encode_with_special_tokens(text).len() as u32
+    } else {
+        // Fallback estimation: ~4 characters per token
+        (text.len() / 4) as u32
+    }
+}
+
 impl Default for AIParameters {
     fn default() -> Self {
         Self {
@@ -0,0 +1,126 @@
+//! Classification, summarization and entity extraction built on plain text
+//! generation, for providers without dedicated endpoints for them
+
+use crate::types::*;
+use std::collections::HashMap;
+
+pub(crate) fn classification_prompt(request: &TextClassificationRequest) -> (String, AIParameters) {
+    let prompt = format!(
+        "Classify the following text into one of these categories: {}\n\nText: {}\n\nRespond with only the category name.",
+        request.categories.join(", "),
+        request.text
+    );
+
+    let parameters = AIParameters {
+        max_tokens: Some(50),
+        temperature: Some(0.0),
+        ..Default::default()
+    };
+
+    (prompt, parameters)
+}
+
+pub(crate) fn classification_result(
+    request: &TextClassificationRequest,
+    response: &str,
+    usage: TokenUsage,
+) -> TextClassificationResult {
+    let response = response.trim().to_lowercase();
+
+    // Find the best matching category
+    let category = request
+        .categories
+        .iter()
+        .find(|cat| response.contains(&cat.to_lowercase()))
+        .unwrap_or(&request.categories[0])
+        .clone();
+
+    // Create confidence scores (simplified)
+    let all_scores: HashMap<String, f32> = request
+        .categories
+        .iter()
+        .map(|cat| (cat.clone(), if cat == &category { 0.9 } else { 0.1 }))
+        .collect();
+
+    TextClassificationResult {
+        category,
+        confidence: 0.9,
+        all_scores,
+        usage,
+    }
+}
+
+pub(crate) fn summarization_prompt(request: &TextSummarizationRequest) -> (String, AIParameters) {
+    let style_instruction = match request.style.as_ref().unwrap_or(&SummarizationStyle::Abstractive) {
+        SummarizationStyle::Extractive => "Extract the most important sentences",
+        SummarizationStyle::Abstractive => "Create a concise summary in your own words",
+        SummarizationStyle::Bullet => "Create a bullet-point summary",
+        SummarizationStyle::Executive => "Create an executive summary",
+    };
+
+    let max_length = request.max_length.unwrap_or(200);
+    let prompt = format!(
+        "{} of the following text in approximately {} words:\n\n{}",
+        style_instruction, max_length, request.text
+    );
+
+    let parameters = AIParameters {
+        max_tokens: Some(max_length * 2),
+        temperature: Some(0.3),
+        ..Default::default()
+    };
+
+    (prompt, parameters)
+}
+
+pub(crate) fn summarization_result(
+    request: &TextSummarizationRequest,
+    summary: String,
+    usage: TokenUsage,
+) -> TextSummarizationResult {
+    // Extract key points (simplified)
+    let key_points: Vec<String> = summary
+        .split('\n')
+        .filter(|line| !line.trim().is_empty())
+        .take(5)
+        .map(|s| s.trim().to_string())
+        .collect();
+
+    let compression_ratio = summary.len() as f32 / request.text.len() as f32;
+
+    TextSummarizationResult {
+        summary,
+        key_points,
+        compression_ratio,
+        usage,
+    }
+}
+
+pub(crate) fn extraction_prompt(request: &EntityExtractionRequest) -> (String, AIParameters) {
+    let entity_types_str = request
+        .entity_types
+        .iter()
+        .map(|et| format!("{:?}", et))
+        .collect::<Vec<_>>()
+        .join(", ");
+
+    let prompt = format!(
+        "Extract entities of the following types from the text: {}\n\nText: {}\n\nReturn the entities in JSON format with fields: text, type, start_position, end_position, confidence",
+        entity_types_str, request.text
+    );
+
+    let parameters = AIParameters {
+        max_tokens: Some(1000),
+        temperature: Some(0.0),
+        ..Default::default()
+    };
+
+    (prompt, parameters)
+}
+
+pub(crate) fn extraction_result(response: &str, usage: TokenUsage) -> EntityExtractionResult {
+    // Parse JSON response (simplified)
+    let entities: Vec<ExtractedEntity> = serde_json::from_str(response).unwrap_or_default();
+
+    EntityExtractionResult { entities, usage }
+}
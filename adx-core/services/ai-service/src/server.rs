@@ -1,10 +1,16 @@
 use crate::config::Config;
 use crate::error::AIResult;
 use crate::handlers::*;
-use crate::services::{AIService, HealthMonitor, UsageTracker};
+use crate::moderation::Moderator;
+use crate::pii::PiiRedactor;
+use crate::services::{
+    AIService, AudioService, AuditLog, BatchProcessor, BudgetEnforcer, EmbeddingService, HealthMonitor, ImageService, PromptTemplateStore,
+    ProviderRouter, ResponseCache, StreamSessionStore, UsageTracker,
+};
 use axum::{
+    extract::DefaultBodyLimit,
     middleware,
-    routing::{get, post},
+    routing::{delete, get, post},
     Router,
 };
 // use shared::middleware::{auth_middleware, tenant_middleware, cors_middleware}; // Commented out until shared crate is available
@@ -29,15 +35,108 @@ pub async fn create_app(config: Config) -> AIResult<Router> {
         60, // Check every 60 seconds
     ));
     
+    let stream_sessions = Arc::new(StreamSessionStore::new(ai_service.get_db_pool()));
+    let embedding_service = Arc::new(EmbeddingService::new(&config, ai_service.get_db_pool()));
+    let budget = Arc::new(BudgetEnforcer::new(
+        &config.budget,
+        ai_service.get_db_pool(),
+        ai_service.get_model_registry(),
+    ));
+    let prompt_templates = Arc::new(PromptTemplateStore::new(ai_service.get_db_pool()));
+    let pii = Arc::new(PiiRedactor::new(
+        &config,
+        ai_service.get_db_pool(),
+        ai_service.get_provider_manager(),
+        ai_service.get_model_registry(),
+    ));
+    let moderator = Arc::new(Moderator::new(&config, ai_service.get_db_pool(), pii.clone()));
+    let router = Arc::new(ProviderRouter::new(
+        ai_service.get_db_pool(),
+        ai_service.get_model_registry(),
+        ai_service.get_provider_manager(),
+    ));
+    let audio = Arc::new(AudioService::new(
+        &config,
+        ai_service.get_db_pool(),
+        embedding_service.clone(),
+        moderator.clone(),
+    ));
+    let images = Arc::new(ImageService::new(&config, moderator.clone()));
+    let batches = Arc::new(BatchProcessor::new(
+        &config,
+        ai_service.get_db_pool(),
+        ai_service.get_provider_manager(),
+        ai_service.get_model_registry(),
+        moderator.clone(),
+    ));
+    let response_cache = Arc::new(ResponseCache::new(
+        &config.cache,
+        ai_service.get_db_pool(),
+        embedding_service.clone(),
+        router.clone(),
+    ));
+    let audit = Arc::new(AuditLog::new(&config, ai_service.get_db_pool()));
+    
     // Start health monitoring
     health_monitor.start_monitoring().await;
     
+    // Purge audit records past their tenant's retention
+    audit.clone().start_purging(std::time::Duration::from_secs(config.audit.purge_interval_seconds));
+    
+    // Purge PII tokens past their time to live
+    pii.start_purging(std::time::Duration::from_secs(config.pii.purge_interval_seconds));
+    
     let app_state = Arc::new(AppStateInner {
         ai_service,
         usage_tracker,
         health_monitor,
+        stream_sessions,
+        embedding_service,
+        budget,
+        prompt_templates,
+        moderator,
+        router,
+        audio,
+        images,
+        response_cache,
+        batches,
+        audit,
     });
     
+    // Streaming endpoints stay open for the whole generation, so they are
+    // kept out of the request timeout
+    let streaming = Router::new()
+        .route("/api/v1/generate/stream", post(generate_text_stream))
+        .route("/api/v1/generate/streams", post(start_text_stream))
+        .route("/api/v1/generate/streams/:stream_id", get(follow_text_stream))
+        .layer(
+            ServiceBuilder::new()
+                .layer(TraceLayer::new_for_http())
+                .layer(cors_middleware())
+        );
+    
+    // Audio uploads are larger than the default body limit, and transcribing
+    // them can outlast the request timeout
+    let audio_uploads = Router::new()
+        .route("/api/v1/audio/transcriptions", post(transcribe_audio))
+        .layer(DefaultBodyLimit::max(config.audio.max_upload_bytes + 64 * 1024))
+        .layer(
+            ServiceBuilder::new()
+                .layer(TraceLayer::new_for_http())
+                .layer(cors_middleware())
+        );
+    
+    // Image requests wait on both the provider and file-service; generating
+    // several images can outlast the request timeout
+    let images = Router::new()
+        .route("/api/v1/images/generations", post(generate_image))
+        .route("/api/v1/images/analyses", post(analyze_image))
+        .layer(
+            ServiceBuilder::new()
+                .layer(TraceLayer::new_for_http())
+                .layer(cors_middleware())
+        );
+    
     // Create router
     let app = Router::new()
         // Health and status endpoints (no auth required)
@@ -54,10 +153,78 @@ pub async fn create_app(config: Config) -> AIResult<Router> {
         .route("/api/v1/classify", post(classify_text))
         .route("/api/v1/summarize", post(summarize_text))
         .route("/api/v1/extract-entities", post(extract_entities))
+        .route("/api/v1/embeddings", post(create_embeddings))
+        .route("/api/v1/audio/speech", post(synthesize_speech))
+        .route("/api/v1/audio/transcriptions/jobs", post(create_transcription_job))
+        .route("/api/v1/audio/transcriptions/jobs/:job_id", get(get_transcription_job))
+        .route("/api/v1/batches", post(create_batch))
+        .route("/api/v1/batches/:batch_id", get(get_batch))
+        .route("/api/v1/batches/:batch_id/items", get(get_batch_items))
+        
+        // Tenant-scoped vector store
+        .route("/api/v1/vectors/:collection/upsert", post(upsert_vectors))
+        .route("/api/v1/vectors/:collection/query", post(query_vectors))
+        .route("/api/v1/vectors/:collection/delete", post(delete_vectors))
+        
+        // Versioned prompt templates
+        .route("/api/v1/prompts", get(list_prompt_templates).post(create_prompt_template))
+        .route("/api/v1/prompts/:name", get(get_prompt_template_versions).delete(delete_prompt_template))
+        .route("/api/v1/prompts/:name/versions", post(create_prompt_template_version))
+        .route(
+            "/api/v1/prompts/:name/versions/:version",
+            get(get_prompt_template_version).delete(delete_prompt_template_version),
+        )
+        .route("/api/v1/prompts/:name/render", post(render_prompt_template))
+        
+        // Tenant moderation policy
+        .route(
+            "/api/v1/moderation/policy",
+            get(get_moderation_policy).put(put_moderation_policy).delete(delete_moderation_policy),
+        )
+        
+        // Tenant response cache
+        .route(
+            "/api/v1/cache/policy",
+            get(get_cache_policy).put(put_cache_policy).delete(delete_cache_policy),
+        )
+        .route("/api/v1/cache/invalidate", post(invalidate_cache))
+        .route("/api/v1/cache/stats", get(get_cache_stats))
+        
+        // AI interaction audit log
+        .route("/api/v1/audit/records", get(list_audit_records))
+        .route("/api/v1/audit/records/:record_id", get(get_audit_record))
+        .route(
+            "/api/v1/audit/policy",
+            get(get_audit_policy).put(put_audit_policy).delete(delete_audit_policy),
+        )
+        .route("/api/v1/audit/export", post(export_audit_records))
+        
+        // PII detection and redaction, also used by file-service and
+        // security-service
+        .route("/api/v1/pii/detect", post(detect_pii))
+        .route("/api/v1/pii/redact", post(redact_pii))
+        .route("/api/v1/pii/restore", post(restore_pii))
+        .route(
+            "/api/v1/pii/policy",
+            get(get_pii_policy).put(put_pii_policy).delete(delete_pii_policy),
+        )
+        
+        // Tenant provider routing policy
+        .route(
+            "/api/v1/routing/policy",
+            get(get_routing_policy).put(put_routing_policy).delete(delete_routing_policy),
+        )
+        
+        // Local model management
+        .route("/api/v1/local/models", get(list_local_models))
+        .route("/api/v1/local/models/pulls", get(list_local_model_pulls).post(pull_local_model))
+        .route("/api/v1/local/models/:model", delete(delete_local_model))
         
         // Usage and analytics endpoints
         .route("/api/v1/usage/stats", get(get_usage_stats))
         .route("/api/v1/usage/costs", get(get_cost_breakdown))
+        .route("/api/v1/usage/users", get(get_usage_by_user))
+        .route("/api/v1/usage/budget", get(get_budget_status))
         
         // Add middleware
         .layer(
@@ -68,6 +235,9 @@ pub async fn create_app(config: Config) -> AIResult<Router> {
                 // .layer(middleware::from_fn(tenant_middleware))
                 // .layer(middleware::from_fn(auth_middleware))
         )
+        .merge(streaming)
+        .merge(audio_uploads)
+        .merge(images)
         .with_state(app_state);
     
     Ok(app)
@@ -92,6 +262,12 @@ mod tests {
                     default_model: "gpt-3.5-turbo".to_string(),
                     max_tokens: 4096,
                     temperature: 0.7,
+                    embedding_model: "text-embedding-3-small".to_string(),
+                    transcription_model: "whisper-1".to_string(),
+                    speech_model: "tts-1".to_string(),
+                    speech_voice: "alloy".to_string(),
+                    image_model: "dall-e-3".to_string(),
+                    vision_model: "gpt-4o-mini".to_string(),
                 },
                 anthropic: crate::config::AnthropicConfig {
                     api_key: "test".to_string(),
@@ -103,6 +279,43 @@ mod tests {
                     enabled: false,
                     base_url: "http://localhost:11434".to_string(),
                     models: vec!["llama2-7b".to_string()],
+                    embedding_model: "nomic-embed-text".to_string(),
+                    model_tags: std::collections::HashMap::new(),
+                    resources: crate::config::LocalResourceConfig {
+                        num_gpu: None,
+                        main_gpu: None,
+                        num_thread: None,
+                        num_ctx: None,
+                        keep_alive: "5m".to_string(),
+                    },
+                    speech_base_url: None,
+                    transcription_model: "Systran/faster-whisper-small".to_string(),
+                    speech_model: "tts-1".to_string(),
+                    speech_voice: "alloy".to_string(),
+                    vision_model: "llava".to_string(),
+                },
+                azure_openai: crate::config::AzureOpenAIConfig {
+                    api_key: String::new(),
+                    endpoint: String::new(),
+                    api_version: "2024-06-01".to_string(),
+                    default_model: "azure-gpt-35-turbo".to_string(),
+                    deployments: std::collections::HashMap::new(),
+                    max_tokens: 4096,
+                },
+                bedrock: crate::config::BedrockConfig {
+                    region: "us-east-1".to_string(),
+                    access_key_id: String::new(),
+                    secret_access_key: String::new(),
+                    session_token: None,
+                    default_model: "anthropic.claude-3-haiku-20240307-v1:0".to_string(),
+                    models: vec!["anthropic.claude-3-haiku-20240307-v1:0".to_string()],
+                    max_tokens: 4096,
+                },
+                gemini: crate::config::GeminiConfig {
+                    api_key: String::new(),
+                    base_url: None,
+                    default_model: "gemini-1.5-flash".to_string(),
+                    max_tokens: 8192,
                 },
             },
             monitoring: crate::config::MonitoringConfig {
@@ -116,6 +329,57 @@ mod tests {
                 rate_limit_per_minute: 60,
                 max_request_size: 1048576,
             },
+            budget: crate::config::BudgetConfig {
+                enabled: false,
+                license_service_url: "http://localhost:8087".to_string(),
+                over_budget_action: crate::config::OverBudgetAction::Reject,
+                cache_seconds: 300,
+            },
+            moderation: crate::config::ModerationConfig {
+                enabled: false,
+                provider_check: false,
+                blocked_keywords: vec![],
+                security_service_url: "http://localhost:8087".to_string(),
+            },
+            audio: crate::config::AudioConfig {
+                file_service_url: "http://localhost:8083".to_string(),
+                max_upload_bytes: 10485760,
+                max_file_bytes: 26214400,
+                transcript_collection: "transcripts".to_string(),
+            },
+            images: crate::config::ImageConfig {
+                file_service_url: "http://localhost:8083".to_string(),
+                max_file_bytes: 20971520,
+                max_images: 10,
+                preview_max_edge: 256,
+            },
+            cache: crate::config::CacheConfig {
+                enabled: false,
+                ttl_seconds: 86400,
+                semantic_matching: false,
+                similarity_threshold: 0.95,
+            },
+            batch: crate::config::BatchConfig {
+                file_service_url: "http://localhost:8083".to_string(),
+                max_items: 1000,
+                max_file_bytes: 1048576,
+                max_concurrency: 4,
+            },
+            audit: crate::config::AuditConfig {
+                enabled: true,
+                store_prompts: true,
+                store_responses: true,
+                redact_pii: true,
+                retention_days: 90,
+                purge_interval_seconds: 3600,
+            },
+            pii: crate::config::PiiConfig {
+                enabled: false,
+                detect_patterns: true,
+                ner_model: "llama2-7b".to_string(),
+                token_ttl_seconds: 604800,
+                purge_interval_seconds: 3600,
+            },
         };
         
         // This test would require a test database setup
@@ -1,7 +1,7 @@
 use crate::config::Config;
 use crate::error::{AIError, AIResult};
 use crate::models::AIModelRegistry;
-use crate::providers::AIProviderManager;
+use crate::providers::{AIProviderManager, TextStream};
 use crate::types::*;
 use sqlx::PgPool;
 use std::sync::Arc;
@@ -32,7 +32,11 @@ impl AIService {
         let provider_manager = Arc::new(AIProviderManager::new(&config.ai_providers));
         
         // Initialize model registry
-        let model_registry = Arc::new(AIModelRegistry::new());
+        let mut model_registry = AIModelRegistry::new();
+        if config.ai_providers.local.enabled {
+            model_registry.register_local_models(&config.ai_providers.local.models);
+        }
+        let model_registry = Arc::new(model_registry);
         
         Ok(Self {
             config,
@@ -186,4 +190,20 @@ impl AIService {
             metadata: result.metadata,
         })
     }
+    
+    pub async fn stream_ai_request(&self, request: AIRequest) -> AIResult<TextStream> {
+        let model_info = self.model_registry.get_model(&request.model)
+            .ok_or_else(|| AIError::ModelNotAvailable(format!("Model {} not found", request.model)))?;
+        
+        let provider = self.provider_manager.get_provider(&model_info.provider)?;
+        
+        let text_request = TextGenerationRequest {
+            prompt: request.prompt,
+            model: Some(request.model),
+            parameters: request.parameters,
+            context: request.context,
+        };
+        
+        provider.generate_text_stream(&text_request).await
+    }
 }
\ No newline at end of file
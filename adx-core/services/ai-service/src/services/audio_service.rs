@@ -0,0 +1,418 @@
+use crate::audio::{SpeechProvider, SpeechProviderManager};
+use crate::config::{AudioConfig, Config};
+use crate::error::{AIError, AIResult};
+use crate::files::FileServiceClient;
+use crate::moderation::Moderator;
+use crate::services::embedding_service::{validate_collection, UpsertResult};
+use crate::services::EmbeddingService;
+use crate::types::*;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Characters of input the speech APIs accept per request
+const MAX_SPEECH_CHARACTERS: usize = 4096;
+
+/// Transcript characters per indexed document
+const PASSAGE_CHARACTERS: usize = 1000;
+
+const JOB_COLUMNS: &str = "id, tenant_id, user_id, workflow_id, file_id, collection, status, transcript, documents, error, created_at, updated_at";
+
+/// The outcome of running a transcription job. `transcription` and `index`
+/// are only set by the run that did the work, so usage is metered once.
+pub struct TranscribedFile {
+    pub job: TranscriptionJob,
+    pub transcription: Option<TranscriptionResult>,
+    pub index: Option<UpsertResult>,
+}
+
+/// Speech-to-text and text-to-speech. Transcripts are moderated as model
+/// output and synthesized text as input.
+pub struct AudioService {
+    config: AudioConfig,
+    providers: SpeechProviderManager,
+    files: FileServiceClient,
+    db_pool: Arc<PgPool>,
+    embedding_service: Arc<EmbeddingService>,
+    moderator: Arc<Moderator>,
+}
+
+impl AudioService {
+    pub fn new(
+        config: &Config,
+        db_pool: Arc<PgPool>,
+        embedding_service: Arc<EmbeddingService>,
+        moderator: Arc<Moderator>,
+    ) -> Self {
+        Self {
+            config: config.audio.clone(),
+            providers: SpeechProviderManager::new(&config.ai_providers),
+            files: FileServiceClient::new(&config.audio.file_service_url),
+            db_pool,
+            embedding_service,
+            moderator,
+        }
+    }
+
+    pub fn max_upload_bytes(&self) -> usize {
+        self.config.max_upload_bytes
+    }
+
+    pub async fn transcribe(&self, request: TranscriptionRequest, provider: Option<&AIProvider>) -> AIResult<TranscriptionResult> {
+        if request.audio.is_empty() {
+            return Err(AIError::Validation("Audio is required".to_string()));
+        }
+
+        let provider = self.provider(provider)?;
+        let mut result = provider.transcribe(&request).await?;
+        self.moderate_transcript(&request.context, &mut result).await?;
+        Ok(result)
+    }
+
+    pub async fn synthesize(&self, mut request: SpeechSynthesisRequest, provider: Option<&AIProvider>) -> AIResult<SynthesizedSpeech> {
+        if request.text.trim().is_empty() {
+            return Err(AIError::Validation("text is required".to_string()));
+        }
+        if request.text.chars().count() > MAX_SPEECH_CHARACTERS {
+            return Err(AIError::Validation(format!(
+                "At most {} characters can be synthesized at once",
+                MAX_SPEECH_CHARACTERS
+            )));
+        }
+        if request.speed.is_some_and(|speed| !(0.25..=4.0).contains(&speed)) {
+            return Err(AIError::Validation("speed must be between 0.25 and 4.0".to_string()));
+        }
+
+        request.text = self.moderator
+            .moderate(&request.context, ModerationStage::Input, &request.text)
+            .await?
+            .text;
+
+        let provider = self.provider(provider)?;
+        provider.synthesize(&request).await
+    }
+
+    /// Create the job, or return it when it already exists. The file is
+    /// checked to exist now, so that a wrong id fails the request rather
+    /// than the job.
+    pub async fn create_job(&self, request: &TranscribeFileRequest) -> AIResult<TranscriptionJob> {
+        let context = &request.context;
+        let collection = request.collection.clone().unwrap_or_else(|| self.config.transcript_collection.clone());
+        validate_collection(&collection)?;
+
+        let file = self.files.get(context, request.file_id).await?;
+        if file.file_size as u64 > self.config.max_file_bytes {
+            return Err(AIError::Validation(format!(
+                "File {} is {} bytes; at most {} bytes can be transcribed",
+                request.file_id, file.file_size, self.config.max_file_bytes
+            )));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO ai_transcription_jobs (id, tenant_id, user_id, workflow_id, file_id, collection, status)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            ON CONFLICT (id) DO NOTHING
+            "#,
+        )
+        .bind(request.job_id)
+        .bind(&context.tenant_id)
+        .bind(&context.user_id)
+        .bind(&context.workflow_id)
+        .bind(request.file_id)
+        .bind(&collection)
+        .bind(status_name(&TranscriptionJobStatus::Pending))
+        .execute(&*self.db_pool)
+        .await?;
+
+        self.get_job(&context.tenant_id, request.job_id).await
+    }
+
+    pub async fn get_job(&self, tenant_id: &str, id: Uuid) -> AIResult<TranscriptionJob> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM ai_transcription_jobs WHERE id = $1 AND tenant_id = $2",
+            JOB_COLUMNS
+        ))
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(&*self.db_pool)
+        .await?
+        .ok_or_else(|| AIError::NotFound(format!("Transcription job {} not found", id)))?;
+
+        job_from_row(&row)
+    }
+
+    /// Download, transcribe and index the job's file. A job that already
+    /// completed is returned as it is, so retries do not transcribe twice.
+    pub async fn run_job(&self, request: &TranscribeFileRequest) -> AIResult<TranscribedFile> {
+        let job = self.get_job(&request.context.tenant_id, request.job_id).await?;
+        if job.status == TranscriptionJobStatus::Completed {
+            return Ok(TranscribedFile {
+                job,
+                transcription: None,
+                index: None,
+            });
+        }
+
+        self.set_status(job.id, TranscriptionJobStatus::Running, None).await?;
+
+        match self.transcribe_and_index(&job, request).await {
+            Ok((transcription, index)) => {
+                let documents = index.as_ref().map_or(0, |index| index.upserted as i32);
+                sqlx::query(
+                    r#"
+                    UPDATE ai_transcription_jobs
+                    SET status = $2, transcript = $3, documents = $4, error = NULL
+                    WHERE id = $1
+                    "#,
+                )
+                .bind(job.id)
+                .bind(status_name(&TranscriptionJobStatus::Completed))
+                .bind(serde_json::to_value(&transcription)?)
+                .bind(documents)
+                .execute(&*self.db_pool)
+                .await?;
+
+                Ok(TranscribedFile {
+                    job: self.get_job(&job.tenant_id, job.id).await?,
+                    transcription: Some(transcription),
+                    index,
+                })
+            }
+            Err(e) => {
+                self.set_status(job.id, TranscriptionJobStatus::Failed, Some(&e.to_string())).await?;
+                Err(e)
+            }
+        }
+    }
+
+    async fn transcribe_and_index(
+        &self,
+        job: &TranscriptionJob,
+        request: &TranscribeFileRequest,
+    ) -> AIResult<(TranscriptionResult, Option<UpsertResult>)> {
+        let (file, audio) = self.files
+            .download(&request.context, job.file_id, self.config.max_file_bytes)
+            .await?;
+
+        let transcription = self.transcribe(
+            TranscriptionRequest {
+                audio,
+                filename: file.filename.clone(),
+                language: request.language.clone(),
+                prompt: None,
+                model: request.model.clone(),
+                context: request.context.clone(),
+            },
+            request.provider.as_ref(),
+        ).await?;
+
+        let documents: Vec<UpsertDocument> = passages(&transcription)
+            .into_iter()
+            .enumerate()
+            .map(|(index, (start, end, content))| UpsertDocument {
+                id: format!("{}:{}", job.file_id, index),
+                content,
+                metadata: Some(serde_json::json!({
+                    "source": "transcript",
+                    "file_id": job.file_id,
+                    "filename": file.filename,
+                    "job_id": job.id,
+                    "language": transcription.language,
+                    "start": start,
+                    "end": end,
+                })),
+            })
+            .collect();
+        if documents.is_empty() {
+            return Ok((transcription, None));
+        }
+
+        let index = self.embedding_service.upsert(
+            &job.collection,
+            documents,
+            None,
+            request.provider.as_ref(),
+            request.context.clone(),
+        ).await?;
+
+        Ok((transcription, Some(index)))
+    }
+
+    async fn set_status(&self, id: Uuid, status: TranscriptionJobStatus, error: Option<&str>) -> AIResult<()> {
+        sqlx::query("UPDATE ai_transcription_jobs SET status = $2, error = $3 WHERE id = $1")
+            .bind(id)
+            .bind(status_name(&status))
+            .bind(error)
+            .execute(&*self.db_pool)
+            .await?;
+        Ok(())
+    }
+
+    /// Apply the tenant's moderation policy to the transcript. Segments are
+    /// moderated together, one per line, so redactions reach them too.
+    async fn moderate_transcript(&self, context: &RequestContext, result: &mut TranscriptionResult) -> AIResult<()> {
+        if result.segments.is_empty() {
+            result.text = self.moderator.moderate(context, ModerationStage::Output, &result.text).await?.text;
+            return Ok(());
+        }
+
+        let lines: Vec<&str> = result.segments.iter().map(|segment| segment.text.as_str()).collect();
+        let moderated = self.moderator
+            .moderate(context, ModerationStage::Output, &lines.join("\n"))
+            .await?;
+        if moderated.findings.is_empty() {
+            return Ok(());
+        }
+
+        let lines: Vec<&str> = moderated.text.lines().collect();
+        if lines.len() != result.segments.len() {
+            return Err(AIError::Internal("Moderation changed the transcript's segments".to_string()));
+        }
+        for (segment, line) in result.segments.iter_mut().zip(&lines) {
+            segment.text = line.to_string();
+        }
+        result.text = lines.join(" ");
+        Ok(())
+    }
+
+    fn provider(&self, provider: Option<&AIProvider>) -> AIResult<&dyn SpeechProvider> {
+        match provider {
+            Some(provider) => self.providers.get_provider(provider),
+            None => self.providers.default_provider(),
+        }
+    }
+}
+
+/// The transcript in passages of about `PASSAGE_CHARACTERS`, with the
+/// offsets into the audio they span when the provider returned segments
+fn passages(transcription: &TranscriptionResult) -> Vec<(Option<f64>, Option<f64>, String)> {
+    let pieces: Vec<(Option<f64>, Option<f64>, &str)> = if transcription.segments.is_empty() {
+        transcription.text.split_whitespace().map(|word| (None, None, word)).collect()
+    } else {
+        transcription.segments
+            .iter()
+            .map(|segment| (Some(segment.start), Some(segment.end), segment.text.as_str()))
+            .collect()
+    };
+
+    let mut passages = Vec::new();
+    let mut current: Option<(Option<f64>, Option<f64>, String)> = None;
+    for (start, end, text) in pieces {
+        if text.is_empty() {
+            continue;
+        }
+        match &mut current {
+            Some((_, passage_end, passage)) if passage.len() + text.len() < PASSAGE_CHARACTERS => {
+                passage.push(' ');
+                passage.push_str(text);
+                *passage_end = end;
+            }
+            _ => {
+                passages.extend(current.take());
+                current = Some((start, end, text.to_string()));
+            }
+        }
+    }
+    passages.extend(current);
+    passages
+}
+
+fn status_name(status: &TranscriptionJobStatus) -> &'static str {
+    match status {
+        TranscriptionJobStatus::Pending => "Pending",
+        TranscriptionJobStatus::Running => "Running",
+        TranscriptionJobStatus::Completed => "Completed",
+        TranscriptionJobStatus::Failed => "Failed",
+    }
+}
+
+fn job_from_row(row: &PgRow) -> AIResult<TranscriptionJob> {
+    let status = match row.try_get::<String, _>("status")?.as_str() {
+        "Running" => TranscriptionJobStatus::Running,
+        "Completed" => TranscriptionJobStatus::Completed,
+        "Failed" => TranscriptionJobStatus::Failed,
+        _ => TranscriptionJobStatus::Pending,
+    };
+    let transcript = row
+        .try_get::<Option<serde_json::Value>, _>("transcript")?
+        .map(serde_json::from_value)
+        .transpose()?;
+
+    Ok(TranscriptionJob {
+        id: row.try_get("id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        user_id: row.try_get("user_id")?,
+        workflow_id: row.try_get("workflow_id")?,
+        file_id: row.try_get("file_id")?,
+        collection: row.try_get("collection")?,
+        status,
+        transcript,
+        documents: row.try_get("documents")?,
+        error: row.try_get("error")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn transcription(text: &str, segments: Vec<TranscriptSegment>) -> TranscriptionResult {
+        TranscriptionResult {
+            text: text.to_string(),
+            language: Some("english".to_string()),
+            duration_seconds: 0.0,
+            segments,
+            model: "whisper-1".to_string(),
+            usage: TokenUsage {
+                prompt_tokens: 0,
+                completion_tokens: 0,
+                total_tokens: 0,
+                estimated_cost: 0.0,
+            },
+        }
+    }
+
+    fn segment(start: f64, end: f64, text: &str) -> TranscriptSegment {
+        TranscriptSegment {
+            start,
+            end,
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn test_passages_group_segments() {
+        let long = "a".repeat(PASSAGE_CHARACTERS - 10);
+        let result = transcription("", vec![
+            segment(0.0, 2.0, "Hello there."),
+            segment(2.0, 4.0, "How are you?"),
+            segment(4.0, 60.0, &long),
+        ]);
+
+        let passages = passages(&result);
+        assert_eq!(passages.len(), 2);
+        assert_eq!(passages[0], (Some(0.0), Some(4.0), "Hello there. How are you?".to_string()));
+        assert_eq!(passages[1], (Some(4.0), Some(60.0), long));
+    }
+
+    #[test]
+    fn test_passages_without_segments() {
+        let words = vec!["word"; PASSAGE_CHARACTERS / 4];
+        let result = transcription(&words.join(" "), vec![]);
+
+        let passages = passages(&result);
+        assert_eq!(passages.len(), 2);
+        assert!(passages.iter().all(|(start, end, passage)| {
+            start.is_none() && end.is_none() && passage.len() < PASSAGE_CHARACTERS
+        }));
+        assert!(passages.iter().all(|(_, _, passage)| !passage.is_empty()));
+    }
+
+    #[test]
+    fn test_passages_of_empty_transcript() {
+        assert!(passages(&transcription("", vec![])).is_empty());
+    }
+}
@@ -0,0 +1,385 @@
+use crate::config::{AuditConfig, Config};
+use crate::error::{AIError, AIResult};
+use crate::moderation::rules::{pii_matches, redact};
+use crate::moderation::SecurityEventClient;
+use crate::types::*;
+use chrono::{DateTime, Duration, Utc};
+use sqlx::{postgres::PgRow, PgPool, Row};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Longest a tenant can keep its records: 7 years
+const MAX_RETENTION_DAYS: u32 = 7 * 365;
+
+/// Records listed per request at most
+const MAX_LIST_LIMIT: i64 = 500;
+
+/// Records sent to security-service per export request at most
+const EXPORT_BATCH_SIZE: i64 = 200;
+
+const RECORD_COLUMNS: &str = "id, tenant_id, user_id, workflow_id, capability, model, prompt, response, redactions, outcome, error, cached, prompt_tokens, completion_tokens, estimated_cost, exported_at, created_at, expires_at";
+
+const ALL_PII_TYPES: [PiiType; 5] = [
+    PiiType::Email,
+    PiiType::Phone,
+    PiiType::CreditCard,
+    PiiType::Ssn,
+    PiiType::IpAddress,
+];
+
+/// Tenant-scoped log of AI interactions for compliance review. What is
+/// stored of prompts and responses follows the tenant's audit policy;
+/// failures to record never fail the request.
+pub struct AuditLog {
+    db_pool: Arc<PgPool>,
+    default_policy: AuditPolicy,
+    security_events: SecurityEventClient,
+}
+
+impl AuditLog {
+    pub fn new(config: &Config, db_pool: Arc<PgPool>) -> Self {
+        Self {
+            db_pool,
+            default_policy: default_policy(&config.audit),
+            security_events: SecurityEventClient::new(&config.moderation.security_service_url),
+        }
+    }
+
+    pub async fn policy(&self, tenant_id: &str) -> AIResult<AuditPolicy> {
+        let row = sqlx::query(
+            r#"
+            SELECT enabled, store_prompts, store_responses, redacted_pii_types, retention_days
+            FROM ai_audit_policies
+            WHERE tenant_id = $1
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_optional(&*self.db_pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(AuditPolicy {
+                enabled: row.try_get("enabled")?,
+                store_prompts: row.try_get("store_prompts")?,
+                store_responses: row.try_get("store_responses")?,
+                redacted_pii_types: serde_json::from_value(row.try_get("redacted_pii_types")?)?,
+                retention_days: row.try_get::<i32, _>("retention_days")? as u32,
+            }),
+            None => Ok(self.default_policy.clone()),
+        }
+    }
+
+    /// Store the policy. The retention of records already stored changes
+    /// with it.
+    pub async fn put_policy(&self, tenant_id: &str, policy: &AuditPolicy) -> AIResult<()> {
+        if policy.retention_days == 0 || policy.retention_days > MAX_RETENTION_DAYS {
+            return Err(AIError::Validation(format!(
+                "retention_days must be between 1 and {}",
+                MAX_RETENTION_DAYS
+            )));
+        }
+
+        let mut tx = self.db_pool.begin().await?;
+        sqlx::query(
+            r#"
+            INSERT INTO ai_audit_policies (tenant_id, enabled, store_prompts, store_responses, redacted_pii_types, retention_days)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (tenant_id) DO UPDATE
+            SET enabled = EXCLUDED.enabled,
+                store_prompts = EXCLUDED.store_prompts,
+                store_responses = EXCLUDED.store_responses,
+                redacted_pii_types = EXCLUDED.redacted_pii_types,
+                retention_days = EXCLUDED.retention_days
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(policy.enabled)
+        .bind(policy.store_prompts)
+        .bind(policy.store_responses)
+        .bind(serde_json::to_value(&policy.redacted_pii_types)?)
+        .bind(policy.retention_days as i32)
+        .execute(&mut *tx)
+        .await?;
+        self.apply_retention(&mut tx, tenant_id, policy.retention_days).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Go back to the default policy
+    pub async fn delete_policy(&self, tenant_id: &str) -> AIResult<()> {
+        let mut tx = self.db_pool.begin().await?;
+        sqlx::query("DELETE FROM ai_audit_policies WHERE tenant_id = $1")
+            .bind(tenant_id)
+            .execute(&mut *tx)
+            .await?;
+        self.apply_retention(&mut tx, tenant_id, self.default_policy.retention_days).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// Record the interaction as the tenant's policy says
+    pub async fn record(&self, entry: AuditEntry) {
+        let tenant_id = entry.context.tenant_id.clone();
+        if let Err(e) = self.try_record(entry).await {
+            tracing::warn!("Failed to audit AI interaction of tenant {}: {}", tenant_id, e);
+        }
+    }
+
+    pub async fn list(&self, tenant_id: &str, query: &AuditQuery) -> AIResult<Vec<AuditRecord>> {
+        let limit = query.limit.unwrap_or(100).clamp(1, MAX_LIST_LIMIT);
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM ai_audit_log
+            WHERE tenant_id = $1
+              AND ($2::varchar IS NULL OR user_id = $2)
+              AND ($3::varchar IS NULL OR capability = $3)
+              AND ($4::varchar IS NULL OR outcome = $4)
+              AND ($5::timestamptz IS NULL OR created_at >= $5)
+              AND ($6::timestamptz IS NULL OR created_at < $6)
+            ORDER BY created_at DESC
+            LIMIT $7 OFFSET $8
+            "#,
+            RECORD_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(&query.user_id)
+        .bind(query.capability.as_ref().map(|capability| format!("{:?}", capability)))
+        .bind(query.outcome.map(outcome_name))
+        .bind(query.from)
+        .bind(query.to)
+        .bind(limit)
+        .bind(query.offset.unwrap_or(0).max(0))
+        .fetch_all(&*self.db_pool)
+        .await?;
+
+        rows.iter().map(record_from_row).collect()
+    }
+
+    pub async fn get(&self, tenant_id: &str, id: Uuid) -> AIResult<AuditRecord> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM ai_audit_log WHERE id = $1 AND tenant_id = $2",
+            RECORD_COLUMNS
+        ))
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(&*self.db_pool)
+        .await?
+        .ok_or_else(|| AIError::NotFound(format!("Audit record {} not found", id)))?;
+
+        record_from_row(&row)
+    }
+
+    /// Send records not yet exported to security-service, oldest first and
+    /// at most `EXPORT_BATCH_SIZE` at a time. Returns how many were sent;
+    /// call again until none are left.
+    pub async fn export(&self, tenant_id: &str, from: Option<DateTime<Utc>>, to: Option<DateTime<Utc>>) -> AIResult<u64> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT {}
+            FROM ai_audit_log
+            WHERE tenant_id = $1 AND exported_at IS NULL
+              AND ($2::timestamptz IS NULL OR created_at >= $2)
+              AND ($3::timestamptz IS NULL OR created_at < $3)
+            ORDER BY created_at
+            LIMIT $4
+            "#,
+            RECORD_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(from)
+        .bind(to)
+        .bind(EXPORT_BATCH_SIZE)
+        .fetch_all(&*self.db_pool)
+        .await?;
+
+        let mut exported = 0;
+        for row in &rows {
+            let record = record_from_row(row)?;
+            self.security_events.export_interaction(&record).await?;
+            sqlx::query("UPDATE ai_audit_log SET exported_at = NOW() WHERE id = $1")
+                .bind(record.id)
+                .execute(&*self.db_pool)
+                .await?;
+            exported += 1;
+        }
+
+        Ok(exported)
+    }
+
+    /// Delete records past their retention
+    pub async fn purge_expired(&self) -> AIResult<u64> {
+        let result = sqlx::query("DELETE FROM ai_audit_log WHERE expires_at <= NOW()")
+            .execute(&*self.db_pool)
+            .await?;
+        Ok(result.rows_affected())
+    }
+
+    /// Purge expired records every `interval`
+    pub fn start_purging(self: Arc<Self>, interval: std::time::Duration) {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match self.purge_expired().await {
+                    Ok(0) => {}
+                    Ok(purged) => tracing::info!("Purged {} expired AI audit records", purged),
+                    Err(e) => tracing::warn!("Failed to purge expired AI audit records: {}", e),
+                }
+            }
+        });
+    }
+
+    async fn try_record(&self, entry: AuditEntry) -> AIResult<()> {
+        let policy = self.policy(&entry.context.tenant_id).await?;
+        if !policy.enabled {
+            return Ok(());
+        }
+
+        let mut redactions = 0;
+        let mut stored = |text: &str, store: bool| {
+            if !store {
+                return None;
+            }
+            let (text, redacted) = redact_pii(text, &policy.redacted_pii_types);
+            redactions += redacted as i32;
+            Some(text)
+        };
+        let prompt = stored(&entry.prompt, policy.store_prompts);
+        let response = entry.response.as_deref().and_then(|response| stored(response, policy.store_responses));
+        let usage = entry.usage.unwrap_or(TokenUsage {
+            prompt_tokens: 0,
+            completion_tokens: 0,
+            total_tokens: 0,
+            estimated_cost: 0.0,
+        });
+
+        sqlx::query(
+            r#"
+            INSERT INTO ai_audit_log
+                (id, tenant_id, user_id, workflow_id, capability, model, prompt, response, redactions,
+                 outcome, error, cached, prompt_tokens, completion_tokens, estimated_cost, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(&entry.context.tenant_id)
+        .bind(&entry.context.user_id)
+        .bind(&entry.context.workflow_id)
+        .bind(format!("{:?}", entry.capability))
+        .bind(&entry.model)
+        .bind(prompt)
+        .bind(response)
+        .bind(redactions)
+        .bind(outcome_name(entry.outcome))
+        .bind(&entry.error)
+        .bind(entry.cached)
+        .bind(usage.prompt_tokens as i32)
+        .bind(usage.completion_tokens as i32)
+        .bind(usage.estimated_cost)
+        .bind(Utc::now() + Duration::days(policy.retention_days as i64))
+        .execute(&*self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn apply_retention(
+        &self,
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
+        tenant_id: &str,
+        retention_days: u32,
+    ) -> AIResult<()> {
+        sqlx::query(
+            "UPDATE ai_audit_log SET expires_at = created_at + make_interval(days => $2) WHERE tenant_id = $1",
+        )
+        .bind(tenant_id)
+        .bind(retention_days as i32)
+        .execute(&mut **tx)
+        .await?;
+        Ok(())
+    }
+}
+
+fn default_policy(config: &AuditConfig) -> AuditPolicy {
+    AuditPolicy {
+        enabled: config.enabled,
+        store_prompts: config.store_prompts,
+        store_responses: config.store_responses,
+        redacted_pii_types: if config.redact_pii { ALL_PII_TYPES.to_vec() } else { Vec::new() },
+        retention_days: config.retention_days,
+    }
+}
+
+/// The text with the PII redacted, and how many matches were
+fn redact_pii(text: &str, pii_types: &[PiiType]) -> (String, usize) {
+    let matches = pii_matches(text, pii_types);
+    if matches.is_empty() {
+        return (text.to_string(), 0);
+    }
+    let ranges: Vec<_> = matches.iter().map(|found| found.range.clone()).collect();
+    (redact(text, &ranges), matches.len())
+}
+
+fn outcome_name(outcome: AuditOutcome) -> &'static str {
+    match outcome {
+        AuditOutcome::Success => "Success",
+        AuditOutcome::Blocked => "Blocked",
+        AuditOutcome::Failed => "Failed",
+    }
+}
+
+fn record_from_row(row: &PgRow) -> AIResult<AuditRecord> {
+    let outcome = match row.try_get::<String, _>("outcome")?.as_str() {
+        "Blocked" => AuditOutcome::Blocked,
+        "Failed" => AuditOutcome::Failed,
+        _ => AuditOutcome::Success,
+    };
+    let prompt_tokens = row.try_get::<i32, _>("prompt_tokens")? as u32;
+    let completion_tokens = row.try_get::<i32, _>("completion_tokens")? as u32;
+
+    Ok(AuditRecord {
+        id: row.try_get("id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        user_id: row.try_get("user_id")?,
+        workflow_id: row.try_get("workflow_id")?,
+        capability: row.try_get("capability")?,
+        model: row.try_get("model")?,
+        prompt: row.try_get("prompt")?,
+        response: row.try_get("response")?,
+        redactions: row.try_get("redactions")?,
+        outcome,
+        error: row.try_get("error")?,
+        cached: row.try_get("cached")?,
+        usage: TokenUsage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+            estimated_cost: row.try_get("estimated_cost")?,
+        },
+        exported_at: row.try_get("exported_at")?,
+        created_at: row.try_get("created_at")?,
+        expires_at: row.try_get("expires_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact_pii_counts_matches() {
+        let (text, redacted) = redact_pii(
+            "Reach jane@example.com or 555-123-4567",
+            &[PiiType::Email, PiiType::Phone],
+        );
+        assert_eq!(text, "Reach [REDACTED] or [REDACTED]");
+        assert_eq!(redacted, 2);
+
+        let (text, redacted) = redact_pii("Reach jane@example.com", &[]);
+        assert_eq!(text, "Reach jane@example.com");
+        assert_eq!(redacted, 0);
+    }
+}
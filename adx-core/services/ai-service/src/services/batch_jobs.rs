@@ -0,0 +1,620 @@
+use crate::config::{BatchConfig, Config};
+use crate::error::{AIError, AIResult};
+use crate::files::{FileServiceClient, ListedFile};
+use crate::models::AIModelRegistry;
+use crate::moderation::Moderator;
+use crate::providers::AIProviderManager;
+use crate::types::*;
+use futures::future::try_join_all;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+/// Times a rate limited provider call is retried before the document fails
+const RATE_LIMIT_RETRIES: u32 = 3;
+
+/// Wait before the first retry of a rate limited call; doubled for each retry
+const RATE_LIMIT_BACKOFF: Duration = Duration::from_secs(5);
+
+/// Document formats a batch reads as text
+const TEXT_MIME_TYPES: [&str; 3] = ["application/json", "application/xml", "application/x-yaml"];
+
+const JOB_COLUMNS: &str = "id, tenant_id, user_id, workflow_id, operations, model, status, total_items, completed_items, failed_items, prompt_tokens, completion_tokens, estimated_cost, report_file_id, error, created_at, updated_at";
+
+const ITEM_COLUMNS: &str = "file_id, filename, status, classification, confidence, summary, error";
+
+/// Classification and summarization of many file-service documents. Every
+/// document's outcome is checkpointed as it completes, and a document that
+/// cannot be read or processed fails alone rather than failing the batch.
+pub struct BatchProcessor {
+    config: BatchConfig,
+    files: FileServiceClient,
+    db_pool: Arc<PgPool>,
+    provider_manager: Arc<AIProviderManager>,
+    model_registry: Arc<AIModelRegistry>,
+    moderator: Arc<Moderator>,
+}
+
+impl BatchProcessor {
+    pub fn new(
+        config: &Config,
+        db_pool: Arc<PgPool>,
+        provider_manager: Arc<AIProviderManager>,
+        model_registry: Arc<AIModelRegistry>,
+        moderator: Arc<Moderator>,
+    ) -> Self {
+        Self {
+            config: config.batch.clone(),
+            files: FileServiceClient::new(&config.batch.file_service_url),
+            db_pool,
+            provider_manager,
+            model_registry,
+            moderator,
+        }
+    }
+
+    /// Create the batch with its documents, served by `model`. A batch that
+    /// already exists is picked up where it stopped.
+    pub async fn prepare(&self, request: &PrepareBatchRequest, model: &str) -> AIResult<BatchPlan> {
+        let context = &request.context;
+        if let Some(job) = self.find_job(&context.tenant_id, request.job_id).await? {
+            return self.plan(job).await;
+        }
+
+        validate_operations(&request.operations)?;
+        let files = self.resolve_source(context, &request.source).await?;
+        let (file_ids, filenames): (Vec<Uuid>, Vec<String>) = files.into_iter().unzip();
+
+        let mut tx = self.db_pool.begin().await?;
+        let created = sqlx::query(
+            r#"
+            INSERT INTO ai_batch_jobs (id, tenant_id, user_id, workflow_id, operations, model, status, total_items)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (id) DO NOTHING
+            "#,
+        )
+        .bind(request.job_id)
+        .bind(&context.tenant_id)
+        .bind(&context.user_id)
+        .bind(&context.workflow_id)
+        .bind(serde_json::to_value(&request.operations)?)
+        .bind(model)
+        .bind(job_status_name(&BatchJobStatus::Running))
+        .bind(file_ids.len() as i32)
+        .execute(&mut *tx)
+        .await?;
+
+        if created.rows_affected() == 1 {
+            sqlx::query(
+                r#"
+                INSERT INTO ai_batch_items (job_id, file_id, filename, status)
+                SELECT $1, file_id, filename, $4
+                FROM UNNEST($2::uuid[], $3::text[]) AS files(file_id, filename)
+                "#,
+            )
+            .bind(request.job_id)
+            .bind(&file_ids)
+            .bind(&filenames)
+            .bind(item_status_name(&BatchItemStatus::Pending))
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        self.plan(self.get_job(&context.tenant_id, request.job_id).await?).await
+    }
+
+    pub async fn get_job(&self, tenant_id: &str, id: Uuid) -> AIResult<BatchJob> {
+        self.find_job(tenant_id, id)
+            .await?
+            .ok_or_else(|| AIError::NotFound(format!("Batch {} not found", id)))
+    }
+
+    pub async fn list_items(&self, tenant_id: &str, job_id: Uuid) -> AIResult<Vec<BatchItem>> {
+        let job = self.get_job(tenant_id, job_id).await?;
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM ai_batch_items WHERE job_id = $1 ORDER BY filename, file_id",
+            ITEM_COLUMNS
+        ))
+        .bind(job.id)
+        .fetch_all(&*self.db_pool)
+        .await?;
+
+        rows.iter().map(item_from_row).collect()
+    }
+
+    /// Read the document and run the batch's operations on it. A document
+    /// that was already processed is returned as it was checkpointed.
+    pub async fn process_item(&self, request: &ProcessBatchItemRequest) -> AIResult<ProcessedBatchItem> {
+        let job = self.get_job(&request.context.tenant_id, request.job_id).await?;
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM ai_batch_items WHERE job_id = $1 AND file_id = $2",
+            ITEM_COLUMNS
+        ))
+        .bind(job.id)
+        .bind(request.file_id)
+        .fetch_optional(&*self.db_pool)
+        .await?
+        .ok_or_else(|| AIError::NotFound(format!("File {} is not part of batch {}", request.file_id, job.id)))?;
+
+        let mut processed = ProcessedBatchItem {
+            item: item_from_row(&row)?,
+            model: job.model.clone(),
+            usage: Vec::new(),
+            rate_limited: false,
+        };
+        if processed.item.status != BatchItemStatus::Pending {
+            return Ok(processed);
+        }
+
+        match self.process_document(&job, &request.context, &mut processed).await {
+            Ok(()) => processed.item.status = BatchItemStatus::Completed,
+            Err(e @ AIError::Database(_)) => return Err(e),
+            Err(e) => {
+                processed.item.status = BatchItemStatus::Failed;
+                processed.item.error = Some(e.to_string());
+            }
+        }
+
+        if !self.checkpoint(job.id, &processed).await? {
+            // Another run checkpointed the document first and metered it
+            processed.usage.clear();
+        }
+        Ok(processed)
+    }
+
+    /// Process the batch's pending documents a wave at a time. Waves start
+    /// `max_concurrency` documents wide, halve whenever a provider rate
+    /// limits them, and widen again one document at a time.
+    pub async fn run<F, Fut>(&self, context: &RequestContext, plan: BatchPlan, process: F) -> AIResult<BatchJob>
+    where
+        F: Fn(Uuid) -> Fut,
+        Fut: Future<Output = AIResult<ProcessedBatchItem>>,
+    {
+        let job_id = plan.job.id;
+        let result = async {
+            let mut concurrency = plan.max_concurrency;
+            let mut pending = plan.pending.into_iter().peekable();
+            while pending.peek().is_some() {
+                let wave: Vec<Uuid> = pending.by_ref().take(concurrency).collect();
+                let processed = try_join_all(wave.into_iter().map(&process)).await?;
+                let rate_limited = processed.iter().any(|processed| processed.rate_limited);
+                concurrency = next_concurrency(concurrency, plan.max_concurrency, rate_limited);
+            }
+            self.complete(context, job_id).await
+        }
+        .await;
+
+        if let Err(e) = &result {
+            self.fail(job_id, &e.to_string()).await?;
+        }
+        result
+    }
+
+    /// Store the report of the processed batch in file-service as the
+    /// batch's user
+    pub async fn complete(&self, context: &RequestContext, job_id: Uuid) -> AIResult<BatchJob> {
+        let job = self.get_job(&context.tenant_id, job_id).await?;
+        if job.status == BatchJobStatus::Completed {
+            return Ok(job);
+        }
+
+        let items = self.list_items(&context.tenant_id, job_id).await?;
+        let report_file_id = self.files
+            .upload(
+                context,
+                &format!("batch-{}.csv", job_id),
+                "text/csv",
+                report_csv(&items).into_bytes(),
+                &serde_json::json!({
+                    "source": "ai_batch_report",
+                    "batch_id": job_id,
+                    "model": job.model,
+                }),
+            )
+            .await?;
+
+        sqlx::query("UPDATE ai_batch_jobs SET status = $2, report_file_id = $3, error = NULL WHERE id = $1")
+            .bind(job_id)
+            .bind(job_status_name(&BatchJobStatus::Completed))
+            .bind(report_file_id)
+            .execute(&*self.db_pool)
+            .await?;
+
+        self.get_job(&context.tenant_id, job_id).await
+    }
+
+    /// Mark the batch failed. Its checkpointed documents are kept, so it
+    /// can be resumed by preparing it again.
+    pub async fn fail(&self, job_id: Uuid, error: &str) -> AIResult<()> {
+        sqlx::query("UPDATE ai_batch_jobs SET status = $2, error = $3 WHERE id = $1")
+            .bind(job_id)
+            .bind(job_status_name(&BatchJobStatus::Failed))
+            .bind(error)
+            .execute(&*self.db_pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn find_job(&self, tenant_id: &str, id: Uuid) -> AIResult<Option<BatchJob>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM ai_batch_jobs WHERE id = $1 AND tenant_id = $2",
+            JOB_COLUMNS
+        ))
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(&*self.db_pool)
+        .await?;
+
+        row.as_ref().map(job_from_row).transpose()
+    }
+
+    async fn plan(&self, mut job: BatchJob) -> AIResult<BatchPlan> {
+        let pending: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT file_id FROM ai_batch_items WHERE job_id = $1 AND status = $2 ORDER BY filename, file_id",
+        )
+        .bind(job.id)
+        .bind(item_status_name(&BatchItemStatus::Pending))
+        .fetch_all(&*self.db_pool)
+        .await?;
+
+        if job.status == BatchJobStatus::Failed {
+            sqlx::query("UPDATE ai_batch_jobs SET status = $2, error = NULL WHERE id = $1")
+                .bind(job.id)
+                .bind(job_status_name(&BatchJobStatus::Running))
+                .execute(&*self.db_pool)
+                .await?;
+            job.status = BatchJobStatus::Running;
+            job.error = None;
+        }
+
+        Ok(BatchPlan {
+            job,
+            pending,
+            max_concurrency: self.config.max_concurrency.max(1),
+        })
+    }
+
+    /// The ids and names of the batch's documents
+    async fn resolve_source(&self, context: &RequestContext, source: &BatchSource) -> AIResult<Vec<(Uuid, String)>> {
+        let files = match source {
+            BatchSource::FileIds(file_ids) => {
+                let mut files: Vec<(Uuid, String)> = Vec::with_capacity(file_ids.len());
+                for file_id in file_ids {
+                    // Names are filled in as the documents are read
+                    if !files.iter().any(|(id, _)| id == file_id) {
+                        files.push((*file_id, String::new()));
+                    }
+                }
+                files
+            }
+            BatchSource::Filter(filter) => self.files
+                .list(context, self.config.max_items + 1, |file| matches_filter(filter, file))
+                .await?
+                .into_iter()
+                .map(|file| (file.id, file.filename))
+                .collect(),
+        };
+
+        if files.is_empty() {
+            return Err(AIError::Validation("The batch has no files".to_string()));
+        }
+        if files.len() > self.config.max_items {
+            return Err(AIError::Validation(format!(
+                "At most {} files can be processed in one batch",
+                self.config.max_items
+            )));
+        }
+        Ok(files)
+    }
+
+    async fn process_document(
+        &self,
+        job: &BatchJob,
+        context: &RequestContext,
+        processed: &mut ProcessedBatchItem,
+    ) -> AIResult<()> {
+        let item = &mut processed.item;
+        let (file, content) = self.files
+            .download(context, item.file_id, self.config.max_file_bytes)
+            .await?;
+        item.filename = file.filename;
+
+        if !is_text(&file.mime_type) {
+            return Err(AIError::Validation(format!("{} is not a text document", file.mime_type)));
+        }
+        let text = String::from_utf8(content)
+            .map_err(|_| AIError::Validation("The document is not UTF-8 text".to_string()))?;
+        if text.trim().is_empty() {
+            return Err(AIError::Validation("The document is empty".to_string()));
+        }
+        let text = self.moderator.moderate(context, ModerationStage::Input, &text).await?.text;
+
+        let model = self.model_registry
+            .get_model(&job.model)
+            .ok_or_else(|| AIError::ModelNotAvailable(format!("Model {} not found", job.model)))?;
+        let provider = self.provider_manager.get_provider(&model.provider)?;
+
+        if let Some(categories) = &job.operations.classify {
+            let request = TextClassificationRequest {
+                text: text.clone(),
+                categories: categories.clone(),
+                model: Some(job.model.clone()),
+                context: context.clone(),
+            };
+            let result = retry_rate_limited(&mut processed.rate_limited, || provider.classify_text(&request)).await?;
+            item.classification = Some(result.category);
+            item.confidence = Some(result.confidence);
+            processed.usage.push((AICapability::TextClassification, result.usage));
+        }
+
+        if let Some(summarization) = &job.operations.summarize {
+            let request = TextSummarizationRequest {
+                text,
+                max_length: summarization.max_length,
+                style: summarization.style.clone(),
+                model: Some(job.model.clone()),
+                context: context.clone(),
+            };
+            let result = retry_rate_limited(&mut processed.rate_limited, || provider.summarize_text(&request)).await?;
+            processed.usage.push((AICapability::TextSummarization, result.usage));
+            item.summary = Some(self.moderator.moderate(context, ModerationStage::Output, &result.summary).await?.text);
+        }
+
+        Ok(())
+    }
+
+    /// Record the document's outcome and add its usage to the batch's.
+    /// Returns false when the document was already checkpointed.
+    async fn checkpoint(&self, job_id: Uuid, processed: &ProcessedBatchItem) -> AIResult<bool> {
+        let item = &processed.item;
+        let mut tx = self.db_pool.begin().await?;
+
+        let updated = sqlx::query(
+            r#"
+            UPDATE ai_batch_items
+            SET filename = $3, status = $4, classification = $5, confidence = $6, summary = $7, error = $8
+            WHERE job_id = $1 AND file_id = $2 AND status = $9
+            "#,
+        )
+        .bind(job_id)
+        .bind(item.file_id)
+        .bind(&item.filename)
+        .bind(item_status_name(&item.status))
+        .bind(&item.classification)
+        .bind(item.confidence)
+        .bind(&item.summary)
+        .bind(&item.error)
+        .bind(item_status_name(&BatchItemStatus::Pending))
+        .execute(&mut *tx)
+        .await?;
+        if updated.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        let completed = item.status == BatchItemStatus::Completed;
+        sqlx::query(
+            r#"
+            UPDATE ai_batch_jobs
+            SET completed_items = completed_items + $2,
+                failed_items = failed_items + $3,
+                prompt_tokens = prompt_tokens + $4,
+                completion_tokens = completion_tokens + $5,
+                estimated_cost = estimated_cost + $6
+            WHERE id = $1
+            "#,
+        )
+        .bind(job_id)
+        .bind(completed as i32)
+        .bind(!completed as i32)
+        .bind(processed.usage.iter().map(|(_, usage)| usage.prompt_tokens as i64).sum::<i64>())
+        .bind(processed.usage.iter().map(|(_, usage)| usage.completion_tokens as i64).sum::<i64>())
+        .bind(processed.usage.iter().map(|(_, usage)| usage.estimated_cost).sum::<f64>())
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+}
+
+/// The width of the next wave of a batch
+pub fn next_concurrency(current: usize, max: usize, rate_limited: bool) -> usize {
+    if rate_limited {
+        (current / 2).max(1)
+    } else {
+        (current + 1).min(max)
+    }
+}
+
+/// Whether the error is a provider turning the request away for its rate
+/// limit. Providers report these as API errors, so they are told apart by
+/// their message.
+pub fn is_rate_limited(error: &AIError) -> bool {
+    match error {
+        AIError::RateLimit(_) => true,
+        AIError::AIProvider(message) => {
+            let message = message.to_lowercase();
+            message.contains("rate limit") || message.contains("429") || message.contains("too many requests")
+        }
+        _ => false,
+    }
+}
+
+/// Call the provider, waiting and retrying while it rate limits the call
+async fn retry_rate_limited<T, F, Fut>(rate_limited: &mut bool, call: F) -> AIResult<T>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = AIResult<T>>,
+{
+    let mut backoff = RATE_LIMIT_BACKOFF;
+    for _ in 0..RATE_LIMIT_RETRIES {
+        match call().await {
+            Err(e) if is_rate_limited(&e) => {
+                *rate_limited = true;
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            result => return result,
+        }
+    }
+    call().await
+}
+
+fn validate_operations(operations: &BatchOperations) -> AIResult<()> {
+    if operations.classify.is_none() && operations.summarize.is_none() {
+        return Err(AIError::Validation("At least one of classify and summarize is required".to_string()));
+    }
+    if operations.classify.as_ref().is_some_and(|categories| categories.is_empty()) {
+        return Err(AIError::Validation("classify needs at least one category".to_string()));
+    }
+    Ok(())
+}
+
+fn matches_filter(filter: &BatchFileFilter, file: &ListedFile) -> bool {
+    file.status == "Ready"
+        && (filter.mime_types.is_empty() || filter.mime_types.iter().any(|mime_type| mime_type == &file.mime_type))
+        && filter.filename_contains.as_ref().map_or(true, |part| {
+            file.filename.to_lowercase().contains(&part.to_lowercase())
+        })
+        && filter.created_after.map_or(true, |after| file.created_at >= after)
+        && filter.created_before.map_or(true, |before| file.created_at < before)
+}
+
+fn is_text(mime_type: &str) -> bool {
+    mime_type.starts_with("text/") || TEXT_MIME_TYPES.contains(&mime_type)
+}
+
+/// One row per document
+fn report_csv(items: &[BatchItem]) -> String {
+    let mut csv = String::from("file_id,filename,status,classification,confidence,summary,error\n");
+    for item in items {
+        let fields = [
+            item.file_id.to_string(),
+            item.filename.clone(),
+            item_status_name(&item.status).to_string(),
+            item.classification.clone().unwrap_or_default(),
+            item.confidence.map(|confidence| confidence.to_string()).unwrap_or_default(),
+            item.summary.clone().unwrap_or_default(),
+            item.error.clone().unwrap_or_default(),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        csv.push_str(&fields.join(","));
+        csv.push('\n');
+    }
+    csv
+}
+
+fn csv_field(field: &str) -> String {
+    if field.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", field.replace('"', "\"\""))
+    } else {
+        field.to_string()
+    }
+}
+
+fn job_status_name(status: &BatchJobStatus) -> &'static str {
+    match status {
+        BatchJobStatus::Pending => "Pending",
+        BatchJobStatus::Running => "Running",
+        BatchJobStatus::Completed => "Completed",
+        BatchJobStatus::Failed => "Failed",
+    }
+}
+
+fn item_status_name(status: &BatchItemStatus) -> &'static str {
+    match status {
+        BatchItemStatus::Pending => "Pending",
+        BatchItemStatus::Completed => "Completed",
+        BatchItemStatus::Failed => "Failed",
+    }
+}
+
+fn job_from_row(row: &PgRow) -> AIResult<BatchJob> {
+    let status = match row.try_get::<String, _>("status")?.as_str() {
+        "Running" => BatchJobStatus::Running,
+        "Completed" => BatchJobStatus::Completed,
+        "Failed" => BatchJobStatus::Failed,
+        _ => BatchJobStatus::Pending,
+    };
+    let prompt_tokens = row.try_get::<i64, _>("prompt_tokens")? as u32;
+    let completion_tokens = row.try_get::<i64, _>("completion_tokens")? as u32;
+
+    Ok(BatchJob {
+        id: row.try_get("id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        user_id: row.try_get("user_id")?,
+        workflow_id: row.try_get("workflow_id")?,
+        operations: serde_json::from_value(row.try_get("operations")?)?,
+        model: row.try_get("model")?,
+        status,
+        total_items: row.try_get("total_items")?,
+        completed_items: row.try_get("completed_items")?,
+        failed_items: row.try_get("failed_items")?,
+        usage: TokenUsage {
+            prompt_tokens,
+            completion_tokens,
+            total_tokens: prompt_tokens + completion_tokens,
+            estimated_cost: row.try_get("estimated_cost")?,
+        },
+        report_file_id: row.try_get("report_file_id")?,
+        error: row.try_get("error")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+fn item_from_row(row: &PgRow) -> AIResult<BatchItem> {
+    let status = match row.try_get::<String, _>("status")?.as_str() {
+        "Completed" => BatchItemStatus::Completed,
+        "Failed" => BatchItemStatus::Failed,
+        _ => BatchItemStatus::Pending,
+    };
+
+    Ok(BatchItem {
+        file_id: row.try_get("file_id")?,
+        filename: row.try_get("filename")?,
+        status,
+        classification: row.try_get("classification")?,
+        confidence: row.try_get("confidence")?,
+        summary: row.try_get("summary")?,
+        error: row.try_get("error")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_concurrency_backs_off_on_rate_limits() {
+        assert_eq!(next_concurrency(8, 8, true), 4);
+        assert_eq!(next_concurrency(1, 8, true), 1);
+        assert_eq!(next_concurrency(4, 8, false), 5);
+        assert_eq!(next_concurrency(8, 8, false), 8);
+
+        assert!(is_rate_limited(&AIError::AIProvider("OpenAI API error: Rate limit reached for gpt-4".to_string())));
+        assert!(!is_rate_limited(&AIError::AIProvider("OpenAI API error: invalid model".to_string())));
+    }
+
+    #[test]
+    fn test_report_quotes_fields() {
+        let items = vec![BatchItem {
+            file_id: Uuid::nil(),
+            filename: "q3, final.txt".to_string(),
+            status: BatchItemStatus::Completed,
+            classification: Some("Financial".to_string()),
+            confidence: Some(0.5),
+            summary: Some("Revenue \"grew\"\nagain".to_string()),
+            error: None,
+        }];
+
+        assert_eq!(
+            report_csv(&items),
+            "file_id,filename,status,classification,confidence,summary,error\n\
+             00000000-0000-0000-0000-000000000000,\"q3, final.txt\",Completed,Financial,0.5,\"Revenue \"\"grew\"\"\nagain\",\n"
+        );
+    }
+}
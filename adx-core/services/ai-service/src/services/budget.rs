@@ -0,0 +1,255 @@
+use crate::config::{BudgetConfig, OverBudgetAction};
+use crate::error::{AIError, AIResult};
+use crate::models::AIModelRegistry;
+use crate::types::*;
+use adx_shared::quotas::{QuotaClient, AI_REQUESTS_QUOTA};
+use adx_shared::ServiceError;
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use serde::{Deserialize, Serialize};
+use sqlx::{PgPool, Row};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use uuid::Uuid;
+
+/// License-service quota holding a tenant's monthly AI budget, in cents.
+/// A limit of -1 means unlimited.
+pub const AI_BUDGET_QUOTA: &str = "ai_monthly_budget_cents";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetStatus {
+    pub tenant_id: String,
+    pub period_start: DateTime<Utc>,
+    /// Estimated cost of this month's requests, in dollars
+    pub spent: f64,
+    /// Monthly budget in dollars, `None` when unlimited
+    pub budget: Option<f64>,
+    pub remaining: Option<f64>,
+    pub exceeded: bool,
+    pub over_budget_action: OverBudgetAction,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuotaSummaryResponse {
+    data: Option<QuotaSummary>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuotaSummary {
+    quotas: Vec<QuotaSummaryItem>,
+}
+
+#[derive(Debug, Deserialize)]
+struct QuotaSummaryItem {
+    quota_name: String,
+    quota_limit: i64,
+}
+
+/// Enforces tenants' monthly AI budgets and daily request quotas. Spend is
+/// the estimated cost of the tenant's recorded usage since the start of the
+/// month.
+pub struct BudgetEnforcer {
+    config: BudgetConfig,
+    db_pool: Arc<PgPool>,
+    model_registry: Arc<AIModelRegistry>,
+    client: reqwest::Client,
+    quotas: QuotaClient,
+    budgets: Mutex<HashMap<String, (Option<f64>, Instant)>>,
+}
+
+impl BudgetEnforcer {
+    pub fn new(config: &BudgetConfig, db_pool: Arc<PgPool>, model_registry: Arc<AIModelRegistry>) -> Self {
+        Self {
+            config: config.clone(),
+            db_pool,
+            model_registry,
+            client: reqwest::Client::new(),
+            quotas: QuotaClient::from_url(&config.license_service_url),
+            budgets: Mutex::new(HashMap::new()),
+        }
+    }
+
+    pub async fn status(&self, tenant_id: &str) -> AIResult<BudgetStatus> {
+        let period_start = month_start(Utc::now());
+        let spent = self.spent_since(tenant_id, period_start).await?;
+        let budget = self.budget(tenant_id).await;
+
+        Ok(BudgetStatus {
+            tenant_id: tenant_id.to_string(),
+            period_start,
+            spent,
+            budget,
+            remaining: budget.map(|budget| (budget - spent).max(0.0)),
+            exceeded: budget.is_some_and(|budget| spent >= budget),
+            over_budget_action: self.config.over_budget_action,
+        })
+    }
+
+    /// The model to serve a request with. Within budget this is the model
+    /// asked for; over budget the request is downgraded or rejected.
+    pub async fn resolve_model(&self, tenant_id: &str, model: &str, capability: &AICapability) -> AIResult<String> {
+        if !self.config.enabled {
+            return Ok(model.to_string());
+        }
+        self.count_request(tenant_id).await?;
+
+        let status = self.status(tenant_id).await?;
+        if !status.exceeded {
+            return Ok(model.to_string());
+        }
+
+        if self.config.over_budget_action == OverBudgetAction::Downgrade {
+            if let Some(cheaper) = self.cheaper_model(model, capability) {
+                tracing::info!(
+                    "Tenant {} is over its AI budget, serving {} instead of {}",
+                    tenant_id, cheaper, model
+                );
+                return Ok(cheaper);
+            }
+        }
+
+        Err(over_budget(&status))
+    }
+
+    /// Reject the request when the tenant is over budget, for requests that
+    /// cannot be served by another model
+    pub async fn ensure_within_budget(&self, tenant_id: &str) -> AIResult<()> {
+        if !self.config.enabled {
+            return Ok(());
+        }
+        self.count_request(tenant_id).await?;
+
+        let status = self.status(tenant_id).await?;
+        if status.exceeded {
+            return Err(over_budget(&status));
+        }
+        Ok(())
+    }
+
+    /// Count the request against the tenant's daily request quota. Like the
+    /// budget, the quota is not enforced while license-service is unreachable.
+    async fn count_request(&self, tenant_id: &str) -> AIResult<()> {
+        // License-service identifies tenants by UUID
+        if Uuid::parse_str(tenant_id).is_err() {
+            return Ok(());
+        }
+
+        match self.quotas.consume(tenant_id, AI_REQUESTS_QUOTA, 1).await {
+            Ok(_) => Ok(()),
+            Err(ServiceError::QuotaExceeded(_)) => Err(AIError::QuotaExceeded(
+                "Daily AI request quota is used up".to_string(),
+            )),
+            Err(e) => {
+                tracing::warn!("Could not count AI request of tenant {}: {}", tenant_id, e);
+                Ok(())
+            }
+        }
+    }
+
+    /// The cheapest model with the capability that costs less than `model`
+    fn cheaper_model(&self, model: &str, capability: &AICapability) -> Option<String> {
+        let current_cost = self.model_registry.get_model(model)?.cost_per_token;
+
+        self.model_registry
+            .get_models_for_capability(capability)
+            .into_iter()
+            .filter(|candidate| candidate.cost_per_token < current_cost)
+            .min_by(|a, b| {
+                a.cost_per_token
+                    .partial_cmp(&b.cost_per_token)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .map(|candidate| candidate.id.clone())
+    }
+
+    async fn spent_since(&self, tenant_id: &str, since: DateTime<Utc>) -> AIResult<f64> {
+        let row = sqlx::query(
+            r#"
+            SELECT COALESCE(SUM(estimated_cost), 0)::FLOAT8 AS spent
+            FROM ai_usage_records
+            WHERE tenant_id = $1 AND request_timestamp >= $2
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(since)
+        .fetch_one(&*self.db_pool)
+        .await?;
+
+        Ok(row.try_get("spent")?)
+    }
+
+    /// The tenant's monthly budget in dollars. Tenants without the quota,
+    /// and lookups that fail, are treated as unlimited so that an outage
+    /// of license-service does not stop AI requests.
+    async fn budget(&self, tenant_id: &str) -> Option<f64> {
+        let cache_for = Duration::from_secs(self.config.cache_seconds);
+        if let Some((budget, fetched_at)) = self.budgets.lock().unwrap().get(tenant_id) {
+            if fetched_at.elapsed() < cache_for {
+                return *budget;
+            }
+        }
+
+        match self.fetch_budget(tenant_id).await {
+            Ok(budget) => {
+                self.budgets
+                    .lock()
+                    .unwrap()
+                    .insert(tenant_id.to_string(), (budget, Instant::now()));
+                budget
+            }
+            Err(e) => {
+                tracing::warn!("Could not fetch AI budget of tenant {}: {}", tenant_id, e);
+                None
+            }
+        }
+    }
+
+    async fn fetch_budget(&self, tenant_id: &str) -> AIResult<Option<f64>> {
+        // License-service identifies tenants by UUID
+        let Ok(tenant_id) = Uuid::parse_str(tenant_id) else {
+            return Ok(None);
+        };
+
+        let response = self
+            .client
+            .get(format!(
+                "{}/quotas/tenant/{}/summary",
+                self.config.license_service_url, tenant_id
+            ))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(AIError::Internal(format!(
+                "License service returned {}",
+                response.status()
+            )));
+        }
+
+        let summary = response.json::<QuotaSummaryResponse>().await?;
+        let limit = summary
+            .data
+            .and_then(|summary| summary.quotas.into_iter().find(|quota| quota.quota_name == AI_BUDGET_QUOTA))
+            .map(|quota| quota.quota_limit);
+
+        Ok(match limit {
+            Some(cents) if cents >= 0 => Some(cents as f64 / 100.0),
+            _ => None,
+        })
+    }
+}
+
+fn over_budget(status: &BudgetStatus) -> AIError {
+    AIError::QuotaExceeded(format!(
+        "Monthly AI budget of ${:.2} is spent (${:.2} used since {})",
+        status.budget.unwrap_or_default(),
+        status.spent,
+        status.period_start.format("%Y-%m-%d")
+    ))
+}
+
+fn month_start(now: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(now.year(), now.month(), 1, 0, 0, 0)
+        .single()
+        .unwrap_or(now)
+}
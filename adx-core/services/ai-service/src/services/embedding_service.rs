@@ -0,0 +1,157 @@
+use crate::config::Config;
+use crate::embeddings::{EmbeddingProvider, EmbeddingProviderManager, PgVectorStore, VectorStore};
+use crate::error::{AIError, AIResult};
+use crate::types::*;
+use sqlx::PgPool;
+use std::sync::Arc;
+
+/// Inputs embedded per provider call
+const MAX_BATCH_SIZE: usize = 256;
+const MAX_TOP_K: usize = 100;
+const MAX_COLLECTION_NAME_LENGTH: usize = 255;
+
+pub struct EmbeddingService {
+    providers: EmbeddingProviderManager,
+    vector_store: Arc<dyn VectorStore>,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct VectorQueryResult {
+    pub matches: Vec<VectorMatch>,
+    pub model: String,
+    pub usage: TokenUsage,
+}
+
+#[derive(Debug, Clone, serde::Serialize)]
+pub struct UpsertResult {
+    pub upserted: usize,
+    pub model: String,
+    pub dimensions: usize,
+    pub usage: TokenUsage,
+}
+
+impl EmbeddingService {
+    pub fn new(config: &Config, db_pool: Arc<PgPool>) -> Self {
+        Self {
+            providers: EmbeddingProviderManager::new(&config.ai_providers),
+            vector_store: Arc::new(PgVectorStore::new(db_pool)),
+        }
+    }
+
+    pub async fn embed(
+        &self,
+        inputs: Vec<String>,
+        model: Option<String>,
+        provider: Option<&AIProvider>,
+        context: RequestContext,
+    ) -> AIResult<EmbeddingResult> {
+        if inputs.is_empty() {
+            return Err(AIError::Validation("At least one input is required".to_string()));
+        }
+        if inputs.len() > MAX_BATCH_SIZE {
+            return Err(AIError::Validation(format!("At most {} inputs can be embedded at once", MAX_BATCH_SIZE)));
+        }
+        if inputs.iter().any(|input| input.trim().is_empty()) {
+            return Err(AIError::Validation("Inputs cannot be empty".to_string()));
+        }
+
+        let provider = self.provider(provider)?;
+        provider.embed(&EmbeddingRequest { inputs, model, context }).await
+    }
+
+    /// Embed the documents and store them in the tenant's collection
+    pub async fn upsert(
+        &self,
+        collection: &str,
+        documents: Vec<UpsertDocument>,
+        model: Option<String>,
+        provider: Option<&AIProvider>,
+        context: RequestContext,
+    ) -> AIResult<UpsertResult> {
+        validate_collection(collection)?;
+        if documents.iter().any(|document| document.id.is_empty()) {
+            return Err(AIError::Validation("Document ids cannot be empty".to_string()));
+        }
+
+        let inputs = documents.iter().map(|document| document.content.clone()).collect();
+        let tenant_id = context.tenant_id.clone();
+        let embedded = self.embed(inputs, model, provider, context).await?;
+
+        let documents: Vec<VectorDocument> = documents
+            .into_iter()
+            .zip(embedded.embeddings)
+            .map(|(document, embedding)| VectorDocument {
+                id: document.id,
+                content: document.content,
+                metadata: document.metadata.unwrap_or_else(|| serde_json::json!({})),
+                embedding,
+            })
+            .collect();
+
+        self.vector_store.upsert(&tenant_id, collection, &embedded.model, &documents).await?;
+
+        Ok(UpsertResult {
+            upserted: documents.len(),
+            model: embedded.model,
+            dimensions: embedded.dimensions,
+            usage: embedded.usage,
+        })
+    }
+
+    /// Documents of the tenant's collection most similar to `query`. The
+    /// query must use the model the documents were stored with.
+    pub async fn query(
+        &self,
+        collection: &str,
+        query: String,
+        top_k: usize,
+        filter: Option<&serde_json::Value>,
+        model: Option<String>,
+        provider: Option<&AIProvider>,
+        context: RequestContext,
+    ) -> AIResult<VectorQueryResult> {
+        validate_collection(collection)?;
+        if top_k == 0 || top_k > MAX_TOP_K {
+            return Err(AIError::Validation(format!("top_k must be between 1 and {}", MAX_TOP_K)));
+        }
+        if filter.is_some_and(|filter| !filter.is_object()) {
+            return Err(AIError::Validation("filter must be a JSON object".to_string()));
+        }
+
+        let tenant_id = context.tenant_id.clone();
+        let mut embedded = self.embed(vec![query], model, provider, context).await?;
+        let embedding = embedded.embeddings.remove(0);
+
+        let matches = self.vector_store
+            .query(&tenant_id, collection, &embedded.model, &embedding, top_k, filter)
+            .await?;
+
+        Ok(VectorQueryResult {
+            matches,
+            model: embedded.model,
+            usage: embedded.usage,
+        })
+    }
+
+    pub async fn delete(&self, tenant_id: &str, collection: &str, ids: &[String]) -> AIResult<u64> {
+        validate_collection(collection)?;
+        self.vector_store.delete(tenant_id, collection, ids).await
+    }
+
+    fn provider(&self, provider: Option<&AIProvider>) -> AIResult<&dyn EmbeddingProvider> {
+        match provider {
+            Some(provider) => self.providers.get_provider(provider),
+            None => self.providers.default_provider(),
+        }
+    }
+}
+
+pub(crate) fn validate_collection(collection: &str) -> AIResult<()> {
+    if collection.is_empty() || collection.len() > MAX_COLLECTION_NAME_LENGTH {
+        return Err(AIError::Validation(format!(
+            "Collection names must be 1 to {} characters",
+            MAX_COLLECTION_NAME_LENGTH
+        )));
+    }
+    Ok(())
+}
@@ -101,6 +101,9 @@ impl HealthMonitor {
             ("gpt-4", AIProvider::OpenAI),
             ("claude-3-sonnet-20240229", AIProvider::Anthropic),
             ("llama2-7b", AIProvider::Local),
+            ("azure-gpt-35-turbo", AIProvider::AzureOpenAI),
+            ("anthropic.claude-3-haiku-20240307-v1:0", AIProvider::Bedrock),
+            ("gemini-1.5-flash", AIProvider::Gemini),
         ];
         
         for (model_id, provider_type) in sample_models {
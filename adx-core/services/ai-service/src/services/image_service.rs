@@ -0,0 +1,184 @@
+use crate::config::{Config, ImageConfig};
+use crate::error::{AIError, AIResult};
+use crate::files::FileServiceClient;
+use crate::images::{preview::png_preview, ImageProvider, ImageProviderManager};
+use crate::moderation::Moderator;
+use crate::types::*;
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Characters of prompt the image APIs accept
+const MAX_PROMPT_CHARACTERS: usize = 4000;
+
+/// Images one generation request can draw
+const MAX_GENERATED_IMAGES: u32 = 4;
+
+/// Image formats every vision provider reads
+const ANALYZABLE_MIME_TYPES: [&str; 4] = ["image/png", "image/jpeg", "image/gif", "image/webp"];
+
+/// Image generation and analysis. Images are read from and written to
+/// file-service as the requesting user; prompts are moderated as input and
+/// analyses as output.
+pub struct ImageService {
+    config: ImageConfig,
+    providers: ImageProviderManager,
+    files: FileServiceClient,
+    moderator: Arc<Moderator>,
+}
+
+impl ImageService {
+    pub fn new(config: &Config, moderator: Arc<Moderator>) -> Self {
+        Self {
+            config: config.images.clone(),
+            providers: ImageProviderManager::new(&config.ai_providers),
+            files: FileServiceClient::new(&config.images.file_service_url),
+            moderator,
+        }
+    }
+
+    /// Draw the images and store them in file-service, each with a preview
+    pub async fn generate(&self, mut request: ImageGenerationRequest, provider: Option<&AIProvider>) -> AIResult<ImageGenerationResult> {
+        validate_prompt(&request.prompt)?;
+        if request.count.is_some_and(|count| !(1..=MAX_GENERATED_IMAGES).contains(&count)) {
+            return Err(AIError::Validation(format!(
+                "count must be between 1 and {}",
+                MAX_GENERATED_IMAGES
+            )));
+        }
+
+        request.prompt = self.moderator
+            .moderate(&request.context, ModerationStage::Input, &request.prompt)
+            .await?
+            .text;
+
+        let provider = self.provider(provider)?;
+        let generated = provider.generate_images(&request).await?;
+
+        let mut images = Vec::with_capacity(generated.images.len());
+        for (index, image) in generated.images.into_iter().enumerate() {
+            images.push(self.store(&request, &generated.model, index + 1, image).await?);
+        }
+
+        Ok(ImageGenerationResult {
+            images,
+            model: generated.model,
+            usage: generated.usage,
+        })
+    }
+
+    /// Answer the prompt about the referenced file-service images
+    pub async fn analyze(&self, mut request: ImageAnalysisRequest, provider: Option<&AIProvider>) -> AIResult<ImageAnalysisResult> {
+        validate_prompt(&request.prompt)?;
+        if request.file_ids.is_empty() {
+            return Err(AIError::Validation("file_ids is required".to_string()));
+        }
+        if request.file_ids.len() > self.config.max_images {
+            return Err(AIError::Validation(format!(
+                "At most {} images can be analyzed at once",
+                self.config.max_images
+            )));
+        }
+
+        let mut images = Vec::with_capacity(request.file_ids.len());
+        for file_id in &request.file_ids {
+            let (file, data) = self.files
+                .download(&request.context, *file_id, self.config.max_file_bytes)
+                .await?;
+            if !ANALYZABLE_MIME_TYPES.contains(&file.mime_type.as_str()) {
+                return Err(AIError::Validation(format!(
+                    "File {} is {}; images must be PNG, JPEG, GIF or WebP",
+                    file_id, file.mime_type
+                )));
+            }
+            images.push(ImageInput {
+                data,
+                mime_type: file.mime_type,
+            });
+        }
+
+        request.prompt = self.moderator
+            .moderate(&request.context, ModerationStage::Input, &request.prompt)
+            .await?
+            .text;
+
+        let provider = self.provider(provider)?;
+        let mut result = provider.analyze_images(&request, &images).await?;
+        result.analysis = self.moderator
+            .moderate(&request.context, ModerationStage::Output, &result.analysis)
+            .await?
+            .text;
+        Ok(result)
+    }
+
+    /// Upload the preview first, so the image's metadata can point at it
+    async fn store(
+        &self,
+        request: &ImageGenerationRequest,
+        model: &str,
+        number: usize,
+        image: GeneratedImage,
+    ) -> AIResult<StoredImage> {
+        let context = &request.context;
+        let extension = image.mime_type.strip_prefix("image/").unwrap_or("png");
+
+        let preview_file_id = match png_preview(&image.data, self.config.preview_max_edge) {
+            Some(preview) => Some(
+                self.files
+                    .upload(
+                        context,
+                        &format!("generated-image-{}-preview.png", number),
+                        "image/png",
+                        preview,
+                        &serde_json::json!({
+                            "source": "ai_image_preview",
+                            "model": model,
+                        }),
+                    )
+                    .await?,
+            ),
+            None => None,
+        };
+
+        let file_id: Uuid = self.files
+            .upload(
+                context,
+                &format!("generated-image-{}.{}", number, extension),
+                &image.mime_type,
+                image.data,
+                &serde_json::json!({
+                    "source": "ai_image_generation",
+                    "model": model,
+                    "prompt": request.prompt,
+                    "revised_prompt": image.revised_prompt,
+                    "preview_file_id": preview_file_id,
+                }),
+            )
+            .await?;
+
+        Ok(StoredImage {
+            file_id,
+            preview_file_id,
+            revised_prompt: image.revised_prompt,
+        })
+    }
+
+    fn provider(&self, provider: Option<&AIProvider>) -> AIResult<&dyn ImageProvider> {
+        match provider {
+            Some(provider) => self.providers.get_provider(provider),
+            None => self.providers.default_provider(),
+        }
+    }
+}
+
+fn validate_prompt(prompt: &str) -> AIResult<()> {
+    if prompt.trim().is_empty() {
+        return Err(AIError::Validation("prompt is required".to_string()));
+    }
+    if prompt.chars().count() > MAX_PROMPT_CHARACTERS {
+        return Err(AIError::Validation(format!(
+            "prompt must be at most {} characters",
+            MAX_PROMPT_CHARACTERS
+        )));
+    }
+    Ok(())
+}
@@ -1,7 +1,27 @@
 pub mod ai_service;
+pub mod audio_service;
+pub mod audit_log;
+pub mod batch_jobs;
+pub mod budget;
+pub mod embedding_service;
 pub mod usage_tracker;
 pub mod health_monitor;
+pub mod image_service;
+pub mod prompt_templates;
+pub mod response_cache;
+pub mod routing;
+pub mod stream_sessions;
 
 pub use ai_service::AIService;
+pub use audio_service::AudioService;
+pub use audit_log::AuditLog;
+pub use batch_jobs::BatchProcessor;
+pub use budget::BudgetEnforcer;
+pub use embedding_service::EmbeddingService;
 pub use usage_tracker::UsageTracker;
-pub use health_monitor::HealthMonitor;
\ No newline at end of file
+pub use health_monitor::HealthMonitor;
+pub use image_service::ImageService;
+pub use prompt_templates::PromptTemplateStore;
+pub use response_cache::ResponseCache;
+pub use routing::ProviderRouter;
+pub use stream_sessions::StreamSessionStore;
\ No newline at end of file
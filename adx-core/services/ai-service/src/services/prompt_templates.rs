@@ -0,0 +1,317 @@
+use crate::error::{AIError, AIResult};
+use crate::types::*;
+use serde::Deserialize;
+use sqlx::{postgres::PgRow, PgPool, Row};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use uuid::Uuid;
+
+const TEMPLATE_COLUMNS: &str = "id, tenant_id, name, version, description, template, variables, model, parameters, created_by, created_at";
+
+/// Templates a tenant sees under a name: its own versions, or the built-in
+/// versions when it has none. `$1` is the tenant and `$2` the name.
+const VISIBLE_VERSIONS: &str = r#"
+    name = $2 AND (
+        tenant_id = $1
+        OR (tenant_id IS NULL AND NOT EXISTS (
+            SELECT 1 FROM ai_prompt_templates own WHERE own.tenant_id = $1 AND own.name = $2
+        ))
+    )
+"#;
+
+const MAX_NAME_LENGTH: usize = 255;
+
+/// The content of a template version
+#[derive(Debug, Clone, Deserialize)]
+pub struct PromptTemplateInput {
+    pub description: Option<String>,
+    pub template: String,
+    #[serde(default)]
+    pub variables: Vec<PromptVariable>,
+    pub model: Option<String>,
+    #[serde(default)]
+    pub parameters: AIParameters,
+}
+
+/// Per-tenant storage of versioned prompt templates. Tenants can override
+/// a built-in template by creating their own template with its name.
+pub struct PromptTemplateStore {
+    db_pool: Arc<PgPool>,
+}
+
+impl PromptTemplateStore {
+    pub fn new(db_pool: Arc<PgPool>) -> Self {
+        Self { db_pool }
+    }
+
+    /// Add a version of the tenant's template, creating the template at
+    /// version 1 when it does not exist yet
+    pub async fn create_version(
+        &self,
+        context: &RequestContext,
+        name: &str,
+        input: PromptTemplateInput,
+    ) -> AIResult<PromptTemplate> {
+        validate(name, &input)?;
+
+        let row = sqlx::query(&format!(
+            r#"
+            INSERT INTO ai_prompt_templates (id, tenant_id, name, version, description, template, variables, model, parameters, created_by)
+            SELECT $1, $2, $3, COALESCE(MAX(version), 0) + 1, $4, $5, $6, $7, $8, $9
+            FROM ai_prompt_templates
+            WHERE tenant_id = $2 AND name = $3
+            RETURNING {}
+            "#,
+            TEMPLATE_COLUMNS
+        ))
+        .bind(Uuid::new_v4())
+        .bind(&context.tenant_id)
+        .bind(name)
+        .bind(&input.description)
+        .bind(&input.template)
+        .bind(serde_json::to_value(&input.variables)?)
+        .bind(&input.model)
+        .bind(serde_json::to_value(&input.parameters)?)
+        .bind(&context.user_id)
+        .fetch_one(&*self.db_pool)
+        .await?;
+
+        template_from_row(&row)
+    }
+
+    /// The latest version of every template the tenant can use
+    pub async fn list(&self, tenant_id: &str) -> AIResult<Vec<PromptTemplate>> {
+        let rows = sqlx::query(&format!(
+            r#"
+            SELECT DISTINCT ON (name) {}
+            FROM ai_prompt_templates
+            WHERE tenant_id = $1 OR tenant_id IS NULL
+            ORDER BY name, tenant_id NULLS LAST, version DESC
+            "#,
+            TEMPLATE_COLUMNS
+        ))
+        .bind(tenant_id)
+        .fetch_all(&*self.db_pool)
+        .await?;
+
+        rows.iter().map(template_from_row).collect()
+    }
+
+    /// All versions of a template, newest first
+    pub async fn versions(&self, tenant_id: &str, name: &str) -> AIResult<Vec<PromptTemplate>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM ai_prompt_templates WHERE {} ORDER BY version DESC",
+            TEMPLATE_COLUMNS, VISIBLE_VERSIONS
+        ))
+        .bind(tenant_id)
+        .bind(name)
+        .fetch_all(&*self.db_pool)
+        .await?;
+
+        if rows.is_empty() {
+            return Err(AIError::NotFound(format!("Prompt template {} not found", name)));
+        }
+        rows.iter().map(template_from_row).collect()
+    }
+
+    pub async fn get(&self, tenant_id: &str, reference: &PromptReference) -> AIResult<PromptTemplate> {
+        let row = sqlx::query(&format!(
+            r#"
+            SELECT {} FROM ai_prompt_templates
+            WHERE {} AND ($3::INT IS NULL OR version = $3)
+            ORDER BY version DESC
+            LIMIT 1
+            "#,
+            TEMPLATE_COLUMNS, VISIBLE_VERSIONS
+        ))
+        .bind(tenant_id)
+        .bind(&reference.name)
+        .bind(reference.version)
+        .fetch_optional(&*self.db_pool)
+        .await?
+        .ok_or_else(|| AIError::NotFound(match reference.version {
+            Some(version) => format!("Prompt template {} version {} not found", reference.name, version),
+            None => format!("Prompt template {} not found", reference.name),
+        }))?;
+
+        template_from_row(&row)
+    }
+
+    /// Delete one version of the tenant's template, or all of them.
+    /// Built-in templates cannot be deleted.
+    pub async fn delete(&self, tenant_id: &str, name: &str, version: Option<i32>) -> AIResult<u64> {
+        let result = sqlx::query(
+            "DELETE FROM ai_prompt_templates WHERE tenant_id = $1 AND name = $2 AND ($3::INT IS NULL OR version = $3)",
+        )
+        .bind(tenant_id)
+        .bind(name)
+        .bind(version)
+        .execute(&*self.db_pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(AIError::NotFound(format!("Prompt template {} not found", name)));
+        }
+        Ok(result.rows_affected())
+    }
+
+    /// Fill in the referenced template with `variables`
+    pub async fn render(
+        &self,
+        tenant_id: &str,
+        reference: &PromptReference,
+        variables: &HashMap<String, String>,
+    ) -> AIResult<RenderedPrompt> {
+        let template = self.get(tenant_id, reference).await?;
+        let prompt = render_template(&template, variables)?;
+
+        Ok(RenderedPrompt {
+            name: template.name,
+            version: template.version,
+            prompt,
+            model: template.model,
+            parameters: template.parameters,
+        })
+    }
+}
+
+/// Replace each `{{variable}}` with its value or default
+pub fn render_template(template: &PromptTemplate, variables: &HashMap<String, String>) -> AIResult<String> {
+    let defaults: HashMap<&str, Option<&String>> = template
+        .variables
+        .iter()
+        .map(|variable| (variable.name.as_str(), variable.default.as_ref()))
+        .collect();
+
+    let mut prompt = String::with_capacity(template.template.len());
+    let mut rest = template.template.as_str();
+    while let Some((before, name, after)) = next_placeholder(rest) {
+        prompt.push_str(before);
+        let value = variables
+            .get(name)
+            .or_else(|| defaults.get(name).copied().flatten())
+            .ok_or_else(|| AIError::Validation(format!(
+                "Variable {} of prompt template {} is required",
+                name, template.name
+            )))?;
+        prompt.push_str(value);
+        rest = after;
+    }
+    prompt.push_str(rest);
+
+    Ok(prompt)
+}
+
+/// The text before the next `{{name}}`, the trimmed name, and the text after it
+fn next_placeholder(text: &str) -> Option<(&str, &str, &str)> {
+    let start = text.find("{{")?;
+    let end = start + 2 + text[start + 2..].find("}}")?;
+    Some((&text[..start], text[start + 2..end].trim(), &text[end + 2..]))
+}
+
+fn validate(name: &str, input: &PromptTemplateInput) -> AIResult<()> {
+    let valid_name = !name.is_empty()
+        && name.len() <= MAX_NAME_LENGTH
+        && name.chars().all(|c| c.is_ascii_alphanumeric() || matches!(c, '.' | '_' | '-'));
+    if !valid_name {
+        return Err(AIError::Validation(format!(
+            "Template names must be 1 to {} letters, digits, '.', '_' or '-'",
+            MAX_NAME_LENGTH
+        )));
+    }
+    if input.template.trim().is_empty() {
+        return Err(AIError::Validation("Template cannot be empty".to_string()));
+    }
+
+    let mut declared = HashSet::new();
+    for variable in &input.variables {
+        if !declared.insert(variable.name.as_str()) {
+            return Err(AIError::Validation(format!("Variable {} is declared twice", variable.name)));
+        }
+    }
+
+    let mut rest = input.template.as_str();
+    while let Some((_, name, after)) = next_placeholder(rest) {
+        if !declared.contains(name) {
+            return Err(AIError::Validation(format!("Variable {} is used but not declared", name)));
+        }
+        rest = after;
+    }
+    Ok(())
+}
+
+fn template_from_row(row: &PgRow) -> AIResult<PromptTemplate> {
+    Ok(PromptTemplate {
+        id: row.try_get("id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        name: row.try_get("name")?,
+        version: row.try_get("version")?,
+        description: row.try_get("description")?,
+        template: row.try_get("template")?,
+        variables: serde_json::from_value(row.try_get("variables")?)?,
+        model: row.try_get("model")?,
+        parameters: serde_json::from_value(row.try_get("parameters")?)?,
+        created_by: row.try_get("created_by")?,
+        created_at: row.try_get("created_at")?,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn template(text: &str, variables: Vec<PromptVariable>) -> PromptTemplate {
+        PromptTemplate {
+            id: Uuid::new_v4(),
+            tenant_id: None,
+            name: "test".to_string(),
+            version: 1,
+            description: None,
+            template: text.to_string(),
+            variables,
+            model: None,
+            parameters: AIParameters::default(),
+            created_by: None,
+            created_at: Utc::now(),
+        }
+    }
+
+    fn variable(name: &str, default: Option<&str>) -> PromptVariable {
+        PromptVariable {
+            name: name.to_string(),
+            description: None,
+            default: default.map(str::to_string),
+        }
+    }
+
+    #[test]
+    fn test_render_template() {
+        let template = template(
+            "Welcome {{ name }}, you are a {{role}}.",
+            vec![variable("name", None), variable("role", Some("member"))],
+        );
+
+        let variables = HashMap::from([("name".to_string(), "Ada".to_string())]);
+        assert_eq!(render_template(&template, &variables).unwrap(), "Welcome Ada, you are a member.");
+
+        assert!(render_template(&template, &HashMap::new()).is_err());
+    }
+
+    #[test]
+    fn test_validate_undeclared_variable() {
+        let input = PromptTemplateInput {
+            description: None,
+            template: "Summarize {{text}}".to_string(),
+            variables: vec![],
+            model: None,
+            parameters: AIParameters::default(),
+        };
+
+        assert!(validate("summaries.short", &input).is_err());
+        assert!(validate("summaries.short", &PromptTemplateInput {
+            variables: vec![variable("text", None)],
+            ..input
+        }).is_ok());
+    }
+}
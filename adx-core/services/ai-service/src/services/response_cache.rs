@@ -0,0 +1,429 @@
+use crate::config::CacheConfig;
+use crate::embeddings::vector_store::vector_literal;
+use crate::error::{AIError, AIResult};
+use crate::services::{EmbeddingService, ProviderRouter};
+use crate::types::*;
+use sha2::{Digest, Sha256};
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+use uuid::Uuid;
+
+/// Longest time a response can be cached for: 30 days
+const MAX_TTL_SECONDS: u64 = 30 * 24 * 60 * 60;
+
+/// How a request uses the cache, from its `Cache-Control` header
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum CacheMode {
+    /// Serve a cached response when there is one, and cache new responses
+    Use,
+    /// Skip the lookup but cache the new response (`no-cache`)
+    Refresh,
+    /// Leave the cache alone (`no-store`)
+    Bypass,
+}
+
+/// A completion to look up in the cache
+pub struct CacheRequest<'a> {
+    pub context: &'a RequestContext,
+    pub capability: AICapability,
+    pub model: &'a str,
+    /// The prompt or text the completion is of, after moderation
+    pub prompt: &'a str,
+    /// Everything else that shapes the response, such as categories or
+    /// sampling parameters
+    pub parameters: serde_json::Value,
+}
+
+/// The outcome of a lookup. On a miss it holds what is needed to cache
+/// the response once it is produced.
+pub struct CacheLookup {
+    pub response: Option<serde_json::Value>,
+    /// The embedding of the prompt for similarity matching, which is
+    /// metered like any other
+    pub embedding_usage: Option<(String, TokenUsage)>,
+    pending: Option<PendingEntry>,
+}
+
+struct PendingEntry {
+    tenant_id: String,
+    cache_key: String,
+    parameters_key: String,
+    capability: AICapability,
+    model: String,
+    prompt: String,
+    embedding: Option<(String, Vec<f32>)>,
+    ttl_seconds: u64,
+}
+
+/// Tenant-scoped cache of completions. Exact matches are found by the hash
+/// of the normalized prompt, model and parameters; with semantic matching,
+/// responses to similar prompts with the same model and parameters are
+/// served too. Failures of the cache never fail a request.
+pub struct ResponseCache {
+    db_pool: Arc<PgPool>,
+    default_policy: CachePolicy,
+    embedding_service: Arc<EmbeddingService>,
+    router: Arc<ProviderRouter>,
+}
+
+impl ResponseCache {
+    pub fn new(
+        config: &CacheConfig,
+        db_pool: Arc<PgPool>,
+        embedding_service: Arc<EmbeddingService>,
+        router: Arc<ProviderRouter>,
+    ) -> Self {
+        Self {
+            db_pool,
+            default_policy: CachePolicy {
+                enabled: config.enabled,
+                ttl_seconds: config.ttl_seconds,
+                semantic_matching: config.semantic_matching,
+                similarity_threshold: config.similarity_threshold,
+            },
+            embedding_service,
+            router,
+        }
+    }
+
+    pub async fn policy(&self, tenant_id: &str) -> AIResult<CachePolicy> {
+        let row = sqlx::query(
+            "SELECT enabled, ttl_seconds, semantic_matching, similarity_threshold FROM ai_cache_policies WHERE tenant_id = $1",
+        )
+        .bind(tenant_id)
+        .fetch_optional(&*self.db_pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(CachePolicy {
+                enabled: row.try_get("enabled")?,
+                ttl_seconds: row.try_get::<i32, _>("ttl_seconds")? as u64,
+                semantic_matching: row.try_get("semantic_matching")?,
+                similarity_threshold: row.try_get("similarity_threshold")?,
+            }),
+            None => Ok(self.default_policy.clone()),
+        }
+    }
+
+    pub async fn put_policy(&self, tenant_id: &str, policy: &CachePolicy) -> AIResult<()> {
+        if policy.ttl_seconds == 0 || policy.ttl_seconds > MAX_TTL_SECONDS {
+            return Err(AIError::Validation(format!(
+                "ttl_seconds must be between 1 and {}",
+                MAX_TTL_SECONDS
+            )));
+        }
+        if !(0.5..=1.0).contains(&policy.similarity_threshold) {
+            return Err(AIError::Validation(
+                "similarity_threshold must be between 0.5 and 1.0".to_string(),
+            ));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO ai_cache_policies (tenant_id, enabled, ttl_seconds, semantic_matching, similarity_threshold)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (tenant_id) DO UPDATE
+            SET enabled = EXCLUDED.enabled,
+                ttl_seconds = EXCLUDED.ttl_seconds,
+                semantic_matching = EXCLUDED.semantic_matching,
+                similarity_threshold = EXCLUDED.similarity_threshold
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(policy.enabled)
+        .bind(policy.ttl_seconds as i32)
+        .bind(policy.semantic_matching)
+        .bind(policy.similarity_threshold)
+        .execute(&*self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Go back to the default policy
+    pub async fn delete_policy(&self, tenant_id: &str) -> AIResult<()> {
+        sqlx::query("DELETE FROM ai_cache_policies WHERE tenant_id = $1")
+            .bind(tenant_id)
+            .execute(&*self.db_pool)
+            .await?;
+        Ok(())
+    }
+
+    /// The cached response to the request, if the tenant caches and one is
+    /// cached
+    pub async fn lookup(&self, request: CacheRequest<'_>, mode: CacheMode) -> CacheLookup {
+        let mut lookup = CacheLookup {
+            response: None,
+            embedding_usage: None,
+            pending: None,
+        };
+        if mode == CacheMode::Bypass {
+            return lookup;
+        }
+
+        let tenant_id = &request.context.tenant_id;
+        let policy = match self.policy(tenant_id).await {
+            Ok(policy) if policy.enabled => policy,
+            Ok(_) => return lookup,
+            Err(e) => {
+                tracing::warn!("Failed to read cache policy of tenant {}: {}", tenant_id, e);
+                return lookup;
+            }
+        };
+
+        let prompt = normalize_prompt(request.prompt);
+        let parameters_key = parameters_key(&request.capability, request.model, &request.parameters);
+        let cache_key = hash(&[&parameters_key, &prompt]);
+
+        if mode == CacheMode::Use {
+            match self.find_exact(tenant_id, &cache_key).await {
+                Ok(Some(response)) => {
+                    lookup.response = Some(response);
+                    return lookup;
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Response cache lookup failed for tenant {}: {}", tenant_id, e),
+            }
+        }
+
+        let mut embedding = None;
+        if policy.semantic_matching {
+            match self.embed(request.context, &prompt).await {
+                Ok(embedded) => {
+                    lookup.embedding_usage = Some((embedded.model.clone(), embedded.usage));
+                    embedding = embedded.embeddings.into_iter().next().map(|vector| (embedded.model, vector));
+                }
+                Err(e) => tracing::warn!("Failed to embed prompt for the response cache: {}", e),
+            }
+        }
+
+        if let (CacheMode::Use, Some((model, vector))) = (mode, &embedding) {
+            match self
+                .find_similar(tenant_id, &parameters_key, model, vector, policy.similarity_threshold)
+                .await
+            {
+                Ok(Some(response)) => {
+                    lookup.response = Some(response);
+                    return lookup;
+                }
+                Ok(None) => {}
+                Err(e) => tracing::warn!("Response cache lookup failed for tenant {}: {}", tenant_id, e),
+            }
+        }
+
+        lookup.pending = Some(PendingEntry {
+            tenant_id: tenant_id.clone(),
+            cache_key,
+            parameters_key,
+            capability: request.capability,
+            model: request.model.to_string(),
+            prompt,
+            embedding,
+            ttl_seconds: policy.ttl_seconds,
+        });
+        lookup
+    }
+
+    /// Cache the response produced after a miss. Every cached response
+    /// carries the usage it was produced with, which is what hits save.
+    pub async fn store(&self, lookup: CacheLookup, response: &serde_json::Value) {
+        let Some(entry) = lookup.pending else {
+            return;
+        };
+        let estimated_cost = response["usage"]["estimated_cost"].as_f64().unwrap_or(0.0);
+
+        if let Err(e) = self.insert(&entry, response, estimated_cost).await {
+            tracing::warn!("Failed to cache response for tenant {}: {}", entry.tenant_id, e);
+        }
+    }
+
+    /// Drop the tenant's cached responses, returning how many there were
+    pub async fn invalidate(&self, tenant_id: &str, invalidation: &CacheInvalidation) -> AIResult<u64> {
+        let result = sqlx::query(
+            r#"
+            DELETE FROM ai_response_cache
+            WHERE tenant_id = $1
+              AND ($2::varchar IS NULL OR model = $2)
+              AND ($3::varchar IS NULL OR capability = $3)
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(&invalidation.model)
+        .bind(invalidation.capability.as_ref().map(|capability| format!("{:?}", capability)))
+        .execute(&*self.db_pool)
+        .await?;
+
+        Ok(result.rows_affected())
+    }
+
+    pub async fn stats(&self, tenant_id: &str) -> AIResult<CacheStats> {
+        let row = sqlx::query(
+            r#"
+            SELECT COUNT(*) AS entries,
+                   COALESCE(SUM(hits), 0)::bigint AS hits,
+                   COALESCE(SUM(hits * estimated_cost), 0)::double precision AS saved_cost
+            FROM ai_response_cache
+            WHERE tenant_id = $1 AND expires_at > NOW()
+            "#,
+        )
+        .bind(tenant_id)
+        .fetch_one(&*self.db_pool)
+        .await?;
+
+        Ok(CacheStats {
+            entries: row.try_get("entries")?,
+            hits: row.try_get("hits")?,
+            saved_cost: row.try_get("saved_cost")?,
+        })
+    }
+
+    async fn find_exact(&self, tenant_id: &str, cache_key: &str) -> AIResult<Option<serde_json::Value>> {
+        let row = sqlx::query(
+            r#"
+            UPDATE ai_response_cache
+            SET hits = hits + 1, last_hit_at = NOW()
+            WHERE tenant_id = $1 AND cache_key = $2 AND expires_at > NOW()
+            RETURNING response
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(cache_key)
+        .fetch_optional(&*self.db_pool)
+        .await?;
+
+        Ok(row.map(|row| row.try_get("response")).transpose()?)
+    }
+
+    async fn find_similar(
+        &self,
+        tenant_id: &str,
+        parameters_key: &str,
+        embedding_model: &str,
+        embedding: &[f32],
+        threshold: f32,
+    ) -> AIResult<Option<serde_json::Value>> {
+        let row = sqlx::query(
+            r#"
+            WITH nearest AS (
+                SELECT id, 1 - (embedding <=> $4::vector) AS similarity
+                FROM ai_response_cache
+                WHERE tenant_id = $1 AND parameters_key = $2 AND embedding_model = $3
+                  AND dimensions = $5 AND expires_at > NOW()
+                ORDER BY embedding <=> $4::vector
+                LIMIT 1
+            )
+            UPDATE ai_response_cache AS cache
+            SET hits = cache.hits + 1, last_hit_at = NOW()
+            FROM nearest
+            WHERE cache.id = nearest.id AND nearest.similarity >= $6
+            RETURNING cache.response
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(parameters_key)
+        .bind(embedding_model)
+        .bind(vector_literal(embedding))
+        .bind(embedding.len() as i32)
+        .bind(threshold as f64)
+        .fetch_optional(&*self.db_pool)
+        .await?;
+
+        Ok(row.map(|row| row.try_get("response")).transpose()?)
+    }
+
+    async fn insert(&self, entry: &PendingEntry, response: &serde_json::Value, estimated_cost: f64) -> AIResult<()> {
+        // Expired responses of the tenant are dropped as new ones come in
+        sqlx::query("DELETE FROM ai_response_cache WHERE tenant_id = $1 AND expires_at <= NOW()")
+            .bind(&entry.tenant_id)
+            .execute(&*self.db_pool)
+            .await?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO ai_response_cache
+                (id, tenant_id, cache_key, parameters_key, capability, model, prompt,
+                 embedding_model, dimensions, embedding, response, estimated_cost, expires_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10::vector, $11, $12, NOW() + make_interval(secs => $13))
+            ON CONFLICT (tenant_id, cache_key) DO UPDATE
+            SET embedding_model = EXCLUDED.embedding_model,
+                dimensions = EXCLUDED.dimensions,
+                embedding = EXCLUDED.embedding,
+                response = EXCLUDED.response,
+                estimated_cost = EXCLUDED.estimated_cost,
+                hits = 0,
+                last_hit_at = NULL,
+                created_at = NOW(),
+                expires_at = EXCLUDED.expires_at
+            "#,
+        )
+        .bind(Uuid::new_v4())
+        .bind(&entry.tenant_id)
+        .bind(&entry.cache_key)
+        .bind(&entry.parameters_key)
+        .bind(format!("{:?}", entry.capability))
+        .bind(&entry.model)
+        .bind(&entry.prompt)
+        .bind(entry.embedding.as_ref().map(|(model, _)| model.clone()))
+        .bind(entry.embedding.as_ref().map(|(_, vector)| vector.len() as i32))
+        .bind(entry.embedding.as_ref().map(|(_, vector)| vector_literal(vector)))
+        .bind(response)
+        .bind(estimated_cost)
+        .bind(entry.ttl_seconds as f64)
+        .execute(&*self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Prompts are embedded by a provider the tenant's routing policy allows
+    async fn embed(&self, context: &RequestContext, prompt: &str) -> AIResult<EmbeddingResult> {
+        let provider = self.router.route_provider(&context.tenant_id, None, "embedding").await?;
+        self.embedding_service
+            .embed(vec![prompt.to_string()], None, provider.as_ref(), context.clone())
+            .await
+    }
+}
+
+/// Prompts differing only in whitespace are the same prompt
+fn normalize_prompt(prompt: &str) -> String {
+    prompt.split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn parameters_key(capability: &AICapability, model: &str, parameters: &serde_json::Value) -> String {
+    hash(&[&format!("{:?}", capability), model, &parameters.to_string()])
+}
+
+/// SHA-256 of the parts, each length-prefixed so that parts cannot run
+/// into each other
+fn hash(parts: &[&str]) -> String {
+    let mut hasher = Sha256::new();
+    for part in parts {
+        hasher.update((part.len() as u64).to_be_bytes());
+        hasher.update(part.as_bytes());
+    }
+    hex::encode(hasher.finalize())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cache_key_ignores_whitespace_but_not_parameters() {
+        let parameters = serde_json::json!({ "categories": ["billing", "support"] });
+        let key = |prompt: &str, parameters: &serde_json::Value| {
+            let parameters_key = parameters_key(&AICapability::TextClassification, "gpt-3.5-turbo", parameters);
+            hash(&[&parameters_key, &normalize_prompt(prompt)])
+        };
+
+        assert_eq!(
+            key("My invoice  is wrong\n", &parameters),
+            key(" My invoice is wrong", &parameters)
+        );
+        assert_ne!(
+            key("My invoice is wrong", &parameters),
+            key("My invoice is wrong", &serde_json::json!({ "categories": ["billing"] }))
+        );
+        assert_ne!(key("My invoice is wrong", &parameters), key("My invoice is right", &parameters));
+    }
+}
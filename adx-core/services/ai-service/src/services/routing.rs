@@ -0,0 +1,147 @@
+use crate::error::{AIError, AIResult};
+use crate::models::AIModelRegistry;
+use crate::providers::AIProviderManager;
+use crate::types::*;
+use sqlx::{PgPool, Row};
+use std::sync::Arc;
+
+/// Keeps tenants' requests on the providers their routing policy allows.
+/// Tenants without a policy may use every provider.
+pub struct ProviderRouter {
+    db_pool: Arc<PgPool>,
+    model_registry: Arc<AIModelRegistry>,
+    provider_manager: Arc<AIProviderManager>,
+}
+
+impl ProviderRouter {
+    pub fn new(
+        db_pool: Arc<PgPool>,
+        model_registry: Arc<AIModelRegistry>,
+        provider_manager: Arc<AIProviderManager>,
+    ) -> Self {
+        Self {
+            db_pool,
+            model_registry,
+            provider_manager,
+        }
+    }
+
+    pub async fn policy(&self, tenant_id: &str) -> AIResult<ProviderRoutingPolicy> {
+        let row = sqlx::query(
+            "SELECT self_hosted_only, allowed_providers FROM ai_routing_policies WHERE tenant_id = $1",
+        )
+        .bind(tenant_id)
+        .fetch_optional(&*self.db_pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(ProviderRoutingPolicy {
+                self_hosted_only: row.try_get("self_hosted_only")?,
+                allowed_providers: serde_json::from_value(row.try_get("allowed_providers")?)?,
+            }),
+            None => Ok(ProviderRoutingPolicy::default()),
+        }
+    }
+
+    pub async fn put_policy(&self, tenant_id: &str, policy: &ProviderRoutingPolicy) -> AIResult<()> {
+        if policy.self_hosted_only && !policy.allowed_providers.is_empty()
+            && !policy.allowed_providers.iter().any(AIProvider::is_self_hosted)
+        {
+            return Err(AIError::Validation(
+                "A self-hosted only policy must allow a self-hosted provider".to_string(),
+            ));
+        }
+
+        sqlx::query(
+            r#"
+            INSERT INTO ai_routing_policies (tenant_id, self_hosted_only, allowed_providers)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (tenant_id) DO UPDATE
+            SET self_hosted_only = EXCLUDED.self_hosted_only,
+                allowed_providers = EXCLUDED.allowed_providers
+            "#,
+        )
+        .bind(tenant_id)
+        .bind(policy.self_hosted_only)
+        .bind(serde_json::to_value(&policy.allowed_providers)?)
+        .execute(&*self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Go back to allowing every provider
+    pub async fn delete_policy(&self, tenant_id: &str) -> AIResult<()> {
+        sqlx::query("DELETE FROM ai_routing_policies WHERE tenant_id = $1")
+            .bind(tenant_id)
+            .execute(&*self.db_pool)
+            .await?;
+        Ok(())
+    }
+
+    /// The model to serve a request with: the model asked for when the
+    /// tenant's policy allows its provider, otherwise the cheapest allowed
+    /// model of a configured provider with the capability
+    pub async fn route(&self, tenant_id: &str, model: &str, capability: &AICapability) -> AIResult<String> {
+        let policy = self.policy(tenant_id).await?;
+
+        let requested = self.model_registry.get_model(model);
+        if requested.map_or(true, |requested| policy.allows(&requested.provider)) {
+            return Ok(model.to_string());
+        }
+
+        let routed = self
+            .model_registry
+            .get_models_for_capability(capability)
+            .into_iter()
+            .filter(|candidate| policy.allows(&candidate.provider))
+            .filter(|candidate| self.provider_manager.get_provider(&candidate.provider).is_ok())
+            .min_by(|a, b| {
+                a.cost_per_token
+                    .partial_cmp(&b.cost_per_token)
+                    .unwrap_or(std::cmp::Ordering::Equal)
+            })
+            .map(|candidate| candidate.id.clone())
+            .ok_or_else(|| {
+                AIError::ModelNotAvailable(format!(
+                    "No provider allowed for tenant {} serves {:?}",
+                    tenant_id, capability
+                ))
+            })?;
+
+        tracing::info!(
+            "Routing policy of tenant {} does not allow {}, serving {}",
+            tenant_id, model, routed
+        );
+        Ok(routed)
+    }
+
+    /// The provider of an embedding, audio or image request under the
+    /// tenant's routing policy; `None` leaves the choice to the service.
+    /// All are served by OpenAI and local models.
+    pub async fn route_provider(
+        &self,
+        tenant_id: &str,
+        requested: Option<AIProvider>,
+        purpose: &str,
+    ) -> AIResult<Option<AIProvider>> {
+        let policy = self.policy(tenant_id).await?;
+
+        match requested {
+            Some(provider) if !policy.allows(&provider) => Err(AIError::Authorization(format!(
+                "The routing policy does not allow {:?}",
+                provider
+            ))),
+            Some(provider) => Ok(Some(provider)),
+            None if !policy.self_hosted_only && policy.allowed_providers.is_empty() => Ok(None),
+            None => [AIProvider::OpenAI, AIProvider::Local]
+                .into_iter()
+                .find(|provider| policy.allows(provider))
+                .map(Some)
+                .ok_or_else(|| AIError::ModelNotAvailable(format!(
+                    "No {} provider is allowed by the routing policy",
+                    purpose
+                ))),
+        }
+    }
+}
@@ -0,0 +1,400 @@
+use crate::error::{AIError, AIResult};
+use crate::providers::TextStream;
+use crate::types::*;
+use chrono::Utc;
+use futures::{stream, Stream, StreamExt};
+use sqlx::{postgres::PgRow, PgPool, Row};
+use std::collections::{HashMap, VecDeque};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::broadcast;
+use uuid::Uuid;
+
+/// How often buffered output of a running stream is written to the database
+const FLUSH_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How often followers of a stream recorded elsewhere check for output
+const POLL_INTERVAL: Duration = Duration::from_millis(500);
+
+/// A stream with no output for this long is no longer being recorded
+const STALE_AFTER: Duration = Duration::from_secs(120);
+
+/// Updates kept for followers that fall behind before they must resync
+const UPDATE_BUFFER: usize = 256;
+
+const SESSION_COLUMNS: &str = "id, tenant_id, user_id, workflow_id, model, output, status, finish_reason, usage, error, created_at, updated_at";
+
+#[derive(Debug, Clone)]
+pub enum StreamUpdate {
+    /// Text starting at byte `offset` of the output
+    Delta { offset: usize, text: String },
+    Completed { finish_reason: FinishReason, usage: Option<TokenUsage> },
+    Failed { error: String },
+}
+
+/// A stream being recorded by this process
+struct LiveStream {
+    output: String,
+    updates: broadcast::Sender<StreamUpdate>,
+}
+
+/// Persists streamed generations so that clients can resume them. Output
+/// is written to the database as it arrives; followers in the recording
+/// process also get every chunk as it is produced.
+pub struct StreamSessionStore {
+    db_pool: Arc<PgPool>,
+    live: Mutex<HashMap<Uuid, LiveStream>>,
+}
+
+impl StreamSessionStore {
+    pub fn new(db_pool: Arc<PgPool>) -> Self {
+        Self {
+            db_pool,
+            live: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Create the session, or return it when it already exists
+    pub async fn create(&self, id: Uuid, context: &RequestContext, model: &str) -> AIResult<StreamSession> {
+        sqlx::query(
+            r#"
+            INSERT INTO ai_stream_sessions (id, tenant_id, user_id, workflow_id, model, status)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (id) DO NOTHING
+            "#,
+        )
+        .bind(id)
+        .bind(&context.tenant_id)
+        .bind(&context.user_id)
+        .bind(&context.workflow_id)
+        .bind(model)
+        .bind(status_name(&StreamStatus::Streaming))
+        .execute(&*self.db_pool)
+        .await?;
+
+        self.get(&context.tenant_id, id).await
+    }
+
+    pub async fn get(&self, tenant_id: &str, id: Uuid) -> AIResult<StreamSession> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM ai_stream_sessions WHERE id = $1 AND tenant_id = $2",
+            SESSION_COLUMNS
+        ))
+        .bind(id)
+        .bind(tenant_id)
+        .fetch_optional(&*self.db_pool)
+        .await?
+        .ok_or_else(|| AIError::NotFound(format!("Stream {} not found", id)))?;
+
+        session_from_row(&row)
+    }
+
+    /// Follow a session from byte `offset` of its output until it ends.
+    /// Streams recorded by this process are relayed chunk by chunk; others
+    /// are polled from the database.
+    pub fn resume(self: Arc<Self>, tenant_id: String, id: Uuid, offset: usize) -> impl Stream<Item = StreamUpdate> {
+        let follower = Follower {
+            store: self,
+            tenant_id,
+            id,
+            offset,
+            updates: None,
+            pending: VecDeque::new(),
+            done: false,
+        };
+
+        stream::unfold(follower, |mut follower| async move {
+            follower.next().await.map(|update| (update, follower))
+        })
+    }
+
+    /// Output so far and the updates that follow it, while the stream is
+    /// being recorded by this process
+    fn follow_live(&self, id: Uuid) -> Option<(String, broadcast::Receiver<StreamUpdate>)> {
+        let live = self.live.lock().unwrap();
+        live.get(&id)
+            .map(|stream| (stream.output.clone(), stream.updates.subscribe()))
+    }
+
+    /// Relay a provider stream into the session until it ends. Output
+    /// received before a failure is kept.
+    pub async fn record(&self, session: &StreamSession, stream: TextStream) -> AIResult<StreamSession> {
+        if session.status != StreamStatus::Streaming {
+            sqlx::query("UPDATE ai_stream_sessions SET status = $2, error = NULL WHERE id = $1")
+                .bind(session.id)
+                .bind(status_name(&StreamStatus::Streaming))
+                .execute(&*self.db_pool)
+                .await?;
+        }
+
+        let (updates, _) = broadcast::channel(UPDATE_BUFFER);
+        self.live.lock().unwrap().insert(
+            session.id,
+            LiveStream {
+                output: String::new(),
+                updates,
+            },
+        );
+
+        let (pending, result) = self.relay(session.id, stream).await;
+        let finished = match result {
+            Ok((finish_reason, usage)) => {
+                let finished = self.complete(session.id, &pending, &finish_reason, usage.as_ref()).await;
+                self.publish(session.id, StreamUpdate::Completed { finish_reason, usage });
+                finished
+            }
+            Err(e) => {
+                let finished = self.fail(session.id, &pending, &e.to_string()).await;
+                self.publish(session.id, StreamUpdate::Failed { error: e.to_string() });
+                finished.and(Err(e))
+            }
+        };
+
+        self.live.lock().unwrap().remove(&session.id);
+        finished
+    }
+
+    /// Consume the stream, flushing output periodically. Returns the output
+    /// not yet written and how the stream ended.
+    async fn relay(
+        &self,
+        id: Uuid,
+        mut stream: TextStream,
+    ) -> (String, AIResult<(FinishReason, Option<TokenUsage>)>) {
+        let mut pending = String::new();
+        let mut last_flush = Instant::now();
+        let mut finish_reason = FinishReason::Stop;
+        let mut usage = None;
+
+        while let Some(chunk) = stream.next().await {
+            let chunk = match chunk {
+                Ok(chunk) => chunk,
+                Err(e) => return (pending, Err(e)),
+            };
+
+            if !chunk.delta.is_empty() {
+                self.push_delta(id, &chunk.delta);
+                pending.push_str(&chunk.delta);
+            }
+            if let Some(reason) = chunk.finish_reason {
+                finish_reason = reason;
+            }
+            if chunk.usage.is_some() {
+                usage = chunk.usage;
+            }
+
+            if !pending.is_empty() && last_flush.elapsed() >= FLUSH_INTERVAL {
+                if let Err(e) = self.append(id, &pending).await {
+                    return (pending, Err(e));
+                }
+                pending.clear();
+                last_flush = Instant::now();
+            }
+        }
+
+        (pending, Ok((finish_reason, usage)))
+    }
+
+    fn push_delta(&self, id: Uuid, text: &str) {
+        let mut live = self.live.lock().unwrap();
+        if let Some(stream) = live.get_mut(&id) {
+            let offset = stream.output.len();
+            stream.output.push_str(text);
+            // No followers is not an error
+            let _ = stream.updates.send(StreamUpdate::Delta {
+                offset,
+                text: text.to_string(),
+            });
+        }
+    }
+
+    fn publish(&self, id: Uuid, update: StreamUpdate) {
+        if let Some(stream) = self.live.lock().unwrap().get(&id) {
+            let _ = stream.updates.send(update);
+        }
+    }
+
+    async fn append(&self, id: Uuid, text: &str) -> AIResult<()> {
+        sqlx::query("UPDATE ai_stream_sessions SET output = output || $2 WHERE id = $1")
+            .bind(id)
+            .bind(text)
+            .execute(&*self.db_pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn complete(
+        &self,
+        id: Uuid,
+        pending: &str,
+        finish_reason: &FinishReason,
+        usage: Option<&TokenUsage>,
+    ) -> AIResult<StreamSession> {
+        let usage = usage.map(serde_json::to_value).transpose()?;
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE ai_stream_sessions
+            SET output = output || $2, status = $3, finish_reason = $4, usage = $5
+            WHERE id = $1
+            RETURNING {}
+            "#,
+            SESSION_COLUMNS
+        ))
+        .bind(id)
+        .bind(pending)
+        .bind(status_name(&StreamStatus::Completed))
+        .bind(serde_json::to_value(finish_reason)?.as_str().map(str::to_string))
+        .bind(usage)
+        .fetch_one(&*self.db_pool)
+        .await?;
+
+        session_from_row(&row)
+    }
+
+    async fn fail(&self, id: Uuid, pending: &str, error: &str) -> AIResult<StreamSession> {
+        let row = sqlx::query(&format!(
+            r#"
+            UPDATE ai_stream_sessions
+            SET output = output || $2, status = $3, error = $4
+            WHERE id = $1
+            RETURNING {}
+            "#,
+            SESSION_COLUMNS
+        ))
+        .bind(id)
+        .bind(pending)
+        .bind(status_name(&StreamStatus::Failed))
+        .bind(error)
+        .fetch_one(&*self.db_pool)
+        .await?;
+
+        session_from_row(&row)
+    }
+}
+
+struct Follower {
+    store: Arc<StreamSessionStore>,
+    tenant_id: String,
+    id: Uuid,
+    offset: usize,
+    updates: Option<broadcast::Receiver<StreamUpdate>>,
+    pending: VecDeque<StreamUpdate>,
+    done: bool,
+}
+
+impl Follower {
+    async fn next(&mut self) -> Option<StreamUpdate> {
+        loop {
+            if let Some(update) = self.pending.pop_front() {
+                return Some(update);
+            }
+            if self.done {
+                return None;
+            }
+
+            match self.updates.as_mut() {
+                Some(updates) => match updates.recv().await {
+                    Ok(StreamUpdate::Delta { offset, text }) => self.catch_up(offset, &text),
+                    Ok(update) => self.finish(update),
+                    // Resync from the snapshot or the database
+                    Err(_) => self.updates = None,
+                },
+                None => self.resync().await,
+            }
+        }
+    }
+
+    async fn resync(&mut self) {
+        if let Some((output, updates)) = self.store.follow_live(self.id) {
+            self.catch_up(0, &output);
+            self.updates = Some(updates);
+            return;
+        }
+
+        let session = match self.store.get(&self.tenant_id, self.id).await {
+            Ok(session) => session,
+            Err(e) => return self.finish(StreamUpdate::Failed { error: e.to_string() }),
+        };
+        self.catch_up(0, &session.output);
+
+        match session.status {
+            StreamStatus::Completed => self.finish(StreamUpdate::Completed {
+                finish_reason: session.finish_reason.unwrap_or(FinishReason::Stop),
+                usage: session.usage,
+            }),
+            StreamStatus::Failed => self.finish(StreamUpdate::Failed {
+                error: session.error.unwrap_or_else(|| "Stream failed".to_string()),
+            }),
+            StreamStatus::Streaming => {
+                let idle = (Utc::now() - session.updated_at).to_std().unwrap_or_default();
+                if idle > STALE_AFTER {
+                    self.finish(StreamUpdate::Failed {
+                        error: "Stream is no longer being recorded".to_string(),
+                    });
+                } else {
+                    tokio::time::sleep(POLL_INTERVAL).await;
+                }
+            }
+        }
+    }
+
+    /// Queue the part of `text`, found at `offset` of the output, that the
+    /// follower has not seen yet
+    fn catch_up(&mut self, offset: usize, text: &str) {
+        let end = offset + text.len();
+        if end <= self.offset {
+            return;
+        }
+        if let Some(unseen) = text.get(self.offset.saturating_sub(offset)..) {
+            self.pending.push_back(StreamUpdate::Delta {
+                offset: self.offset.max(offset),
+                text: unseen.to_string(),
+            });
+            self.offset = end;
+        }
+    }
+
+    fn finish(&mut self, update: StreamUpdate) {
+        self.pending.push_back(update);
+        self.done = true;
+    }
+}
+
+fn status_name(status: &StreamStatus) -> &'static str {
+    match status {
+        StreamStatus::Streaming => "Streaming",
+        StreamStatus::Completed => "Completed",
+        StreamStatus::Failed => "Failed",
+    }
+}
+
+fn session_from_row(row: &PgRow) -> AIResult<StreamSession> {
+    let status = match row.try_get::<String, _>("status")?.as_str() {
+        "Completed" => StreamStatus::Completed,
+        "Failed" => StreamStatus::Failed,
+        _ => StreamStatus::Streaming,
+    };
+    let finish_reason = row
+        .try_get::<Option<String>, _>("finish_reason")?
+        .map(|reason| serde_json::from_value(serde_json::Value::String(reason)))
+        .transpose()?;
+    let usage = row
+        .try_get::<Option<serde_json::Value>, _>("usage")?
+        .map(serde_json::from_value)
+        .transpose()?;
+
+    Ok(StreamSession {
+        id: row.try_get("id")?,
+        tenant_id: row.try_get("tenant_id")?,
+        user_id: row.try_get("user_id")?,
+        workflow_id: row.try_get("workflow_id")?,
+        model: row.try_get("model")?,
+        output: row.try_get("output")?,
+        status,
+        finish_reason,
+        usage,
+        error: row.try_get("error")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
@@ -0,0 +1,593 @@
+// Temporal integration through the shared ADX SDK client and worker manager.
+// Workflows and activities are registered with `AdxTemporalWorkerManager`,
+// which polls the service's task queue. Activities called by a workflow run
+// with the retry policy, timeouts and heartbeat timeout of the options the
+// workflow calls them with.
+
+use crate::activities::{QuotaCheckResult, ValidationResult};
+use crate::error::{AIError, AIResult, ActivityError};
+use crate::types::*;
+use adx_shared::temporal::{
+    ActivityExecutionError, ActivityFunction, ActivityRetryPolicy, AdxTemporalWorkerManager, TemporalConfig,
+    TemporalError, WorkflowExecutionError, WorkflowFunction,
+};
+use futures::future::BoxFuture;
+use serde::{de::DeserializeOwned, Serialize};
+use std::collections::HashMap;
+use std::future::Future;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::runtime::Handle;
+use uuid::Uuid;
+
+// Workflow result type
+pub type WorkflowResult<T> = Result<T, WorkflowError>;
+
+#[derive(Debug, thiserror::Error)]
+pub enum WorkflowError {
+    #[error("Activity failed: {0}")]
+    ActivityFailed(String),
+    #[error("Workflow timeout")]
+    Timeout,
+    #[error("Workflow cancelled")]
+    Cancelled,
+}
+
+impl From<ActivityError> for WorkflowError {
+    fn from(error: ActivityError) -> Self {
+        WorkflowError::ActivityFailed(error.to_string())
+    }
+}
+
+/// How a workflow runs its activities
+#[derive(Debug, Clone)]
+pub struct ActivityOptions {
+    /// Longest a single attempt may run
+    pub start_to_close_timeout: Duration,
+    /// Attempts that go this long without a heartbeat are timed out and
+    /// retried, so a lost worker is noticed before the start-to-close timeout
+    pub heartbeat_timeout: Option<Duration>,
+    /// Retryable errors (`ActivityError::is_retryable`) are retried with
+    /// this policy, waiting at least the error's own retry delay
+    pub retry_policy: ActivityRetryPolicy,
+}
+
+impl Default for ActivityOptions {
+    fn default() -> Self {
+        Self {
+            start_to_close_timeout: Duration::from_secs(60),
+            heartbeat_timeout: None,
+            retry_policy: retry_policy(3),
+        }
+    }
+}
+
+impl ActivityOptions {
+    /// Activities that call AI providers. Long generations heartbeat while
+    /// the provider works.
+    pub fn generation() -> Self {
+        Self {
+            start_to_close_timeout: Duration::from_secs(600),
+            heartbeat_timeout: Some(Duration::from_secs(30)),
+            retry_policy: retry_policy(5),
+        }
+    }
+}
+
+fn retry_policy(max_attempts: u32) -> ActivityRetryPolicy {
+    ActivityRetryPolicy {
+        initial_interval: Duration::from_secs(1),
+        max_interval: Duration::from_secs(60),
+        backoff_coefficient: 2.0,
+        max_attempts,
+        non_retryable_errors: Vec::new(),
+    }
+}
+
+/// Heartbeat interval of activities, well within `ActivityOptions::generation`'s
+/// heartbeat timeout
+pub const HEARTBEAT_INTERVAL: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Default)]
+struct Heartbeat {
+    last: Mutex<Option<Instant>>,
+    details: Mutex<Option<serde_json::Value>>,
+}
+
+// Activity context
+#[derive(Debug, Clone)]
+pub struct ActContext {
+    pub activity_id: String,
+    pub workflow_id: String,
+    /// 1 on the first attempt
+    pub attempt: u32,
+    heartbeat: Arc<Heartbeat>,
+    previous_details: Option<serde_json::Value>,
+}
+
+impl ActContext {
+    fn new(workflow_id: &str, activity_id: &str, attempt: u32, previous_details: Option<serde_json::Value>) -> Self {
+        Self {
+            activity_id: activity_id.to_string(),
+            workflow_id: workflow_id.to_string(),
+            attempt,
+            heartbeat: Arc::new(Heartbeat::default()),
+            previous_details,
+        }
+    }
+
+    /// Report the activity alive, with progress to resume from if the
+    /// attempt fails
+    pub fn heartbeat(&self, details: Option<serde_json::Value>) {
+        *self.heartbeat.last.lock().unwrap() = Some(Instant::now());
+        if details.is_some() {
+            *self.heartbeat.details.lock().unwrap() = details;
+        }
+    }
+
+    /// Details of the last heartbeat of the previous attempts
+    pub fn heartbeat_details(&self) -> Option<&serde_json::Value> {
+        self.previous_details.as_ref()
+    }
+
+    /// Heartbeat every `HEARTBEAT_INTERVAL` until `work` completes
+    pub async fn heartbeat_while<F: Future>(&self, work: F) -> F::Output {
+        tokio::pin!(work);
+        let mut ticker = tokio::time::interval(HEARTBEAT_INTERVAL);
+        loop {
+            tokio::select! {
+                output = &mut work => return output,
+                _ = ticker.tick() => self.heartbeat(None),
+            }
+        }
+    }
+
+    fn last_heartbeat(&self) -> Option<Instant> {
+        *self.heartbeat.last.lock().unwrap()
+    }
+
+    fn take_details(&self) -> Option<serde_json::Value> {
+        self.heartbeat.details.lock().unwrap().take()
+    }
+}
+
+type ActivityFn = Arc<
+    dyn Fn(ActContext, serde_json::Value) -> BoxFuture<'static, Result<serde_json::Value, ActivityError>>
+        + Send
+        + Sync,
+>;
+
+/// Activities by name, shared by the worker and the workflows it runs
+#[derive(Clone, Default)]
+struct ActivityRegistry(Arc<RwLock<HashMap<String, ActivityFn>>>);
+
+impl ActivityRegistry {
+    fn insert(&self, name: &str, activity: ActivityFn) {
+        self.0.write().unwrap().insert(name.to_string(), activity);
+    }
+
+    fn get(&self, name: &str) -> Option<ActivityFn> {
+        self.0.read().unwrap().get(name).cloned()
+    }
+}
+
+// Run an activity with its options' retry policy and timeouts
+async fn execute_activity(
+    activities: &ActivityRegistry,
+    workflow_id: &str,
+    name: &str,
+    options: &ActivityOptions,
+    input: serde_json::Value,
+) -> Result<serde_json::Value, ActivityError> {
+    let activity = activities
+        .get(name)
+        .ok_or_else(|| ActivityError::ExternalServiceError(format!("Activity {} is not registered", name)))?;
+    let activity_id = format!("{}-{}", name, Uuid::new_v4());
+
+    let mut attempt = 1;
+    let mut details = None;
+    loop {
+        let ctx = ActContext::new(workflow_id, &activity_id, attempt, details.clone());
+        let error = match run_attempt(activity(ctx.clone(), input.clone()), &ctx, options).await {
+            Ok(output) => return Ok(output),
+            Err(error) => error,
+        };
+        if !error.is_retryable() || attempt >= options.retry_policy.max_attempts {
+            return Err(error);
+        }
+
+        let delay = retry_delay(&options.retry_policy, attempt)
+            .max(Duration::from_secs(error.retry_delay_seconds()));
+        tracing::warn!(
+            "Activity {} of workflow {} failed on attempt {}, retrying in {}s: {}",
+            name,
+            workflow_id,
+            attempt,
+            delay.as_secs(),
+            error
+        );
+        details = ctx.take_details().or(details);
+        tokio::time::sleep(delay).await;
+        attempt += 1;
+    }
+}
+
+async fn run_attempt(
+    work: BoxFuture<'static, Result<serde_json::Value, ActivityError>>,
+    ctx: &ActContext,
+    options: &ActivityOptions,
+) -> Result<serde_json::Value, ActivityError> {
+    let started = Instant::now();
+    let heartbeat_timed_out = async {
+        let Some(timeout) = options.heartbeat_timeout else {
+            return std::future::pending().await;
+        };
+        loop {
+            let deadline = ctx.last_heartbeat().unwrap_or(started) + timeout;
+            if Instant::now() >= deadline {
+                return ActivityError::ExternalServiceError(format!(
+                    "Activity sent no heartbeat for {}s",
+                    timeout.as_secs()
+                ));
+            }
+            tokio::time::sleep_until(deadline.into()).await;
+        }
+    };
+
+    tokio::select! {
+        result = tokio::time::timeout(options.start_to_close_timeout, work) => result.unwrap_or_else(|_| {
+            Err(ActivityError::ExternalServiceError(format!(
+                "Activity timed out after {}s",
+                options.start_to_close_timeout.as_secs()
+            )))
+        }),
+        error = heartbeat_timed_out => Err(error),
+    }
+}
+
+/// Backoff before retrying after the failed `attempt`
+pub fn retry_delay(policy: &ActivityRetryPolicy, attempt: u32) -> Duration {
+    let delay = policy.initial_interval.as_secs_f64() * policy.backoff_coefficient.powi(attempt as i32 - 1);
+    Duration::from_secs_f64(delay.min(policy.max_interval.as_secs_f64()))
+}
+
+// Workflow context
+#[derive(Clone)]
+pub struct WfContext {
+    pub workflow_id: String,
+    pub run_id: String,
+    activities: ActivityRegistry,
+}
+
+impl WfContext {
+    pub fn workflow_info(&self) -> WorkflowInfo {
+        WorkflowInfo {
+            workflow_id: self.workflow_id.clone(),
+            run_id: self.run_id.clone(),
+        }
+    }
+
+    pub fn activity(&self, options: ActivityOptions) -> ActivityStub {
+        ActivityStub {
+            workflow_id: self.workflow_id.clone(),
+            options,
+            activities: self.activities.clone(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct WorkflowInfo {
+    pub workflow_id: String,
+    pub run_id: String,
+}
+
+/// Typed calls of the registered activities from a workflow
+#[derive(Clone)]
+pub struct ActivityStub {
+    workflow_id: String,
+    options: ActivityOptions,
+    activities: ActivityRegistry,
+}
+
+impl ActivityStub {
+    pub async fn generate_text(&self, request: TextGenerationRequest) -> Result<TextGenerationResult, ActivityError> {
+        self.call("generate_text", request).await
+    }
+
+    pub async fn stream_text_generation(&self, request: StreamTextGenerationRequest) -> Result<StreamSession, ActivityError> {
+        self.call("stream_text_generation", request).await
+    }
+
+    pub async fn classify_text(&self, request: TextClassificationRequest) -> Result<TextClassificationResult, ActivityError> {
+        self.call("classify_text", request).await
+    }
+
+    pub async fn summarize_text(&self, request: TextSummarizationRequest) -> Result<TextSummarizationResult, ActivityError> {
+        self.call("summarize_text", request).await
+    }
+
+    pub async fn extract_entities(&self, request: EntityExtractionRequest) -> Result<EntityExtractionResult, ActivityError> {
+        self.call("extract_entities", request).await
+    }
+
+    pub async fn validate_ai_request(&self, request: AIRequest) -> Result<ValidationResult, ActivityError> {
+        self.call("validate_ai_request", request).await
+    }
+
+    pub async fn track_ai_usage(&self, usage_record: AIUsageRecord) -> Result<(), ActivityError> {
+        self.call("track_ai_usage", usage_record).await
+    }
+
+    pub async fn check_ai_quotas(&self, context: RequestContext, capability: AICapability) -> Result<QuotaCheckResult, ActivityError> {
+        self.call("check_ai_quotas", (context, capability)).await
+    }
+
+    pub async fn render_prompt(&self, request: RenderPromptRequest) -> Result<RenderedPrompt, ActivityError> {
+        self.call("render_prompt", request).await
+    }
+
+    pub async fn transcribe_file(&self, request: TranscribeFileRequest) -> Result<TranscriptionJob, ActivityError> {
+        self.call("transcribe_file", request).await
+    }
+
+    pub async fn prepare_batch_job(&self, request: PrepareBatchRequest) -> Result<BatchPlan, ActivityError> {
+        self.call("prepare_batch_job", request).await
+    }
+
+    pub async fn process_batch_item(&self, request: ProcessBatchItemRequest) -> Result<ProcessedBatchItem, ActivityError> {
+        self.call("process_batch_item", request).await
+    }
+
+    pub async fn finish_batch_job(&self, request: FinishBatchRequest) -> Result<BatchJob, ActivityError> {
+        self.call("finish_batch_job", request).await
+    }
+
+    async fn call<Req, Res>(&self, name: &str, request: Req) -> Result<Res, ActivityError>
+    where
+        Req: Serialize,
+        Res: DeserializeOwned,
+    {
+        let input = serde_json::to_value(request).map_err(|e| ActivityError::InvalidInput(e.to_string()))?;
+        let output = execute_activity(&self.activities, &self.workflow_id, name, &self.options, input).await?;
+        serde_json::from_value(output)
+            .map_err(|e| ActivityError::ExternalServiceError(format!("Invalid result of activity {}: {}", name, e)))
+    }
+}
+
+// The worker manager calls workflows and activities synchronously from its
+// task pollers; they are run to completion on the worker's runtime, which
+// must be multi-threaded.
+fn block_on<F: Future>(runtime: &Handle, future: F) -> F::Output {
+    tokio::task::block_in_place(|| runtime.block_on(future))
+}
+
+type WorkflowFn = Box<dyn Fn(Vec<u8>) -> Result<Vec<u8>, WorkflowExecutionError> + Send + Sync>;
+
+struct RegisteredWorkflow {
+    run: WorkflowFn,
+}
+
+impl WorkflowFunction for RegisteredWorkflow {
+    fn execute(&self, input: Vec<u8>) -> Result<Vec<u8>, WorkflowExecutionError> {
+        (self.run)(input)
+    }
+}
+
+struct RegisteredActivity {
+    name: String,
+    activity: ActivityFn,
+    runtime: Handle,
+}
+
+impl ActivityFunction for RegisteredActivity {
+    fn execute(&self, input: Vec<u8>) -> Result<Vec<u8>, ActivityExecutionError> {
+        let input = serde_json::from_slice(&input)
+            .map_err(|e| ActivityExecutionError::SerializationError { message: e.to_string() })?;
+        // Tasks polled from the server are retried by the server, under the
+        // retry policy they were scheduled with
+        let ctx = ActContext::new("", &format!("{}-{}", self.name, Uuid::new_v4()), 1, None);
+        let output = block_on(&self.runtime, (self.activity)(ctx, input)).map_err(|e| {
+            if e.is_retryable() {
+                ActivityExecutionError::Retryable { message: e.to_string() }
+            } else {
+                ActivityExecutionError::NonRetryable { message: e.to_string() }
+            }
+        })?;
+        serde_json::to_vec(&output).map_err(|e| ActivityExecutionError::SerializationError { message: e.to_string() })
+    }
+}
+
+// Worker
+pub struct Worker {
+    manager: AdxTemporalWorkerManager,
+    activities: ActivityRegistry,
+    runtime: Handle,
+}
+
+impl Worker {
+    /// Worker polling `task_queue`. The rest of the Temporal configuration
+    /// follows `ADX_ENVIRONMENT`.
+    pub async fn new(temporal_server_url: &str, task_queue: &str) -> AIResult<Self> {
+        let mut config = TemporalConfig::from_env().map_err(temporal_error)?;
+        config.server_address = temporal_server_url.to_string();
+        config.client_identity = format!("ai-worker-{}", Uuid::new_v4());
+        config.worker.identity = config.client_identity.clone();
+        config.worker.task_queues = vec![task_queue.to_string()];
+
+        let manager = AdxTemporalWorkerManager::new(config, vec![task_queue.to_string()])
+            .await
+            .map_err(temporal_error)?;
+
+        Ok(Self {
+            manager,
+            activities: ActivityRegistry::default(),
+            runtime: Handle::current(),
+        })
+    }
+
+    pub async fn register_wf<F, Fut, Req, Res>(&self, name: &str, workflow: F) -> AIResult<()>
+    where
+        F: Fn(WfContext, Req) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = WorkflowResult<Res>> + Send + 'static,
+        Req: DeserializeOwned + Send + 'static,
+        Res: Serialize + Send + 'static,
+    {
+        let workflow_type = name.to_string();
+        let activities = self.activities.clone();
+        let runtime = self.runtime.clone();
+        let run = move |input: Vec<u8>| {
+            let request: Req = serde_json::from_slice(&input)
+                .map_err(|e| WorkflowExecutionError::SerializationError { message: e.to_string() })?;
+            // Tasks carry only their input, so every execution is identified
+            // by its own run
+            let run_id = Uuid::new_v4().to_string();
+            let ctx = WfContext {
+                workflow_id: format!("{}-{}", workflow_type, run_id),
+                run_id,
+                activities: activities.clone(),
+            };
+            let result = block_on(&runtime, workflow(ctx, request)).map_err(|e| match e {
+                WorkflowError::Cancelled => WorkflowExecutionError::Cancelled { reason: e.to_string() },
+                e => WorkflowExecutionError::ExecutionFailed { message: e.to_string() },
+            })?;
+            serde_json::to_vec(&result).map_err(|e| WorkflowExecutionError::SerializationError { message: e.to_string() })
+        };
+
+        self.manager
+            .register_workflow(name, RegisteredWorkflow { run: Box::new(run) })
+            .await
+            .map_err(temporal_error)
+    }
+
+    pub async fn register_activity<F, Fut, Req, Res>(&self, name: &str, activity: F) -> AIResult<()>
+    where
+        F: Fn(ActContext, Req) -> Fut + Send + Sync + 'static,
+        Fut: Future<Output = Result<Res, ActivityError>> + Send + 'static,
+        Req: DeserializeOwned + Send + 'static,
+        Res: Serialize + Send + 'static,
+    {
+        let activity = Arc::new(activity);
+        let activity: ActivityFn = Arc::new(move |ctx, input| {
+            let activity = activity.clone();
+            Box::pin(async move {
+                let request: Req = serde_json::from_value(input).map_err(|e| ActivityError::InvalidInput(e.to_string()))?;
+                let result = activity(ctx, request).await?;
+                serde_json::to_value(result).map_err(|e| ActivityError::ExternalServiceError(e.to_string()))
+            })
+        });
+        self.activities.insert(name, activity.clone());
+
+        self.manager
+            .register_activity(
+                name,
+                RegisteredActivity {
+                    name: name.to_string(),
+                    activity,
+                    runtime: self.runtime.clone(),
+                },
+            )
+            .await
+            .map_err(temporal_error)
+    }
+
+    pub async fn workflow_count(&self) -> usize {
+        self.manager.workflow_count().await
+    }
+
+    pub async fn activity_count(&self) -> usize {
+        self.manager.activity_count().await
+    }
+
+    /// Poll the task queue until the process is interrupted
+    pub async fn run(self) -> AIResult<()> {
+        self.manager.start().await.map_err(temporal_error)?;
+        tracing::info!(
+            "Temporal worker {} polling {:?}",
+            self.manager.worker_identity(),
+            self.manager.task_queues()
+        );
+
+        tokio::signal::ctrl_c()
+            .await
+            .map_err(|e| AIError::Internal(format!("Failed to listen for shutdown: {}", e)))?;
+
+        tracing::info!("Stopping Temporal worker {}", self.manager.worker_identity());
+        self.manager.stop().await.map_err(temporal_error)
+    }
+}
+
+fn temporal_error(error: TemporalError) -> AIError {
+    AIError::Temporal(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn registry_with(name: &str, activity: ActivityFn) -> ActivityRegistry {
+        let activities = ActivityRegistry::default();
+        activities.insert(name, activity);
+        activities
+    }
+
+    #[test]
+    fn test_retry_delay_backs_off_up_to_max_interval() {
+        let policy = retry_policy(10);
+        assert_eq!(retry_delay(&policy, 1), Duration::from_secs(1));
+        assert_eq!(retry_delay(&policy, 3), Duration::from_secs(4));
+        assert_eq!(retry_delay(&policy, 9), Duration::from_secs(60));
+    }
+
+    #[tokio::test]
+    async fn test_non_retryable_errors_are_not_retried() {
+        let attempts = Arc::new(Mutex::new(0));
+        let counted = attempts.clone();
+        let activities = registry_with("classify_text", Arc::new(move |_ctx, _input| {
+            *counted.lock().unwrap() += 1;
+            Box::pin(async { Err(ActivityError::InvalidInput("empty text".to_string())) })
+        }));
+
+        let result = execute_activity(&activities, "wf", "classify_text", &ActivityOptions::default(), serde_json::Value::Null).await;
+        assert!(matches!(result, Err(ActivityError::InvalidInput(_))));
+        assert_eq!(*attempts.lock().unwrap(), 1);
+    }
+
+    #[tokio::test]
+    async fn test_attempts_without_heartbeats_time_out() {
+        let activities = registry_with("generate_text", Arc::new(|_ctx, _input| {
+            Box::pin(async {
+                tokio::time::sleep(Duration::from_secs(5)).await;
+                Ok(serde_json::Value::Null)
+            })
+        }));
+        let options = ActivityOptions {
+            heartbeat_timeout: Some(Duration::from_millis(50)),
+            retry_policy: retry_policy(1),
+            ..ActivityOptions::generation()
+        };
+
+        let result = execute_activity(&activities, "wf", "generate_text", &options, serde_json::Value::Null).await;
+        assert!(matches!(result, Err(ActivityError::ExternalServiceError(_))));
+    }
+
+    #[tokio::test]
+    async fn test_heartbeating_attempts_complete() {
+        let activities = registry_with("generate_text", Arc::new(|ctx: ActContext, _input| {
+            Box::pin(async move {
+                for _ in 0..4 {
+                    tokio::time::sleep(Duration::from_millis(20)).await;
+                    ctx.heartbeat(None);
+                }
+                Ok(serde_json::json!("done"))
+            })
+        }));
+        let options = ActivityOptions {
+            heartbeat_timeout: Some(Duration::from_millis(50)),
+            ..ActivityOptions::generation()
+        };
+
+        let result = execute_activity(&activities, "wf", "generate_text", &options, serde_json::Value::Null).await;
+        assert_eq!(result.unwrap(), serde_json::json!("done"));
+    }
+}
@@ -4,11 +4,14 @@ use std::collections::HashMap;
 use uuid::Uuid;
 
 // AI Model and Provider Types
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum AIProvider {
     OpenAI,
     Anthropic,
     Local,
+    AzureOpenAI,
+    Bedrock,
+    Gemini,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -33,6 +36,9 @@ pub enum AICapability {
     CodeGeneration,
     ImageGeneration,
     ImageAnalysis,
+    TextEmbedding,
+    SpeechToText,
+    TextToSpeech,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -98,6 +104,721 @@ pub enum FinishReason {
     Error,
 }
 
+impl FinishReason {
+    /// Map the stop reason reported by a provider
+    pub fn from_provider(reason: &str) -> Self {
+        match reason {
+            "length" | "max_tokens" | "MAX_TOKENS" => FinishReason::Length,
+            "content_filter" | "content_filtered" | "guardrail_intervened" | "SAFETY" => FinishReason::ContentFilter,
+            "tool_calls" | "tool_use" | "function_call" => FinishReason::ToolCalls,
+            _ => FinishReason::Stop,
+        }
+    }
+}
+
+// Streaming Types
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TextChunk {
+    pub delta: String,
+    /// Set on the last chunk of a generation
+    pub finish_reason: Option<FinishReason>,
+    pub usage: Option<TokenUsage>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum StreamStatus {
+    Streaming,
+    Completed,
+    Failed,
+}
+
+/// A streamed generation whose output is persisted as it arrives, so that
+/// a client that loses its connection can resume from where it left off
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamSession {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub user_id: String,
+    pub workflow_id: Option<String>,
+    pub model: String,
+    pub output: String,
+    pub status: StreamStatus,
+    pub finish_reason: Option<FinishReason>,
+    pub usage: Option<TokenUsage>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Generate text into the stream session with id `stream_id`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamTextGenerationRequest {
+    pub stream_id: Uuid,
+    pub generation: TextGenerationRequest,
+}
+
+// Embedding Types
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingRequest {
+    pub inputs: Vec<String>,
+    pub model: Option<String>,
+    pub context: RequestContext,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmbeddingResult {
+    /// One vector per input, in input order
+    pub embeddings: Vec<Vec<f32>>,
+    pub model: String,
+    pub dimensions: usize,
+    pub usage: TokenUsage,
+}
+
+/// A document to embed and store in a vector collection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpsertDocument {
+    pub id: String,
+    pub content: String,
+    #[serde(default)]
+    pub metadata: Option<serde_json::Value>,
+}
+
+/// A document and its embedding as kept in a tenant's vector collection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorDocument {
+    pub id: String,
+    pub content: String,
+    pub metadata: serde_json::Value,
+    pub embedding: Vec<f32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VectorMatch {
+    pub id: String,
+    pub content: String,
+    pub metadata: serde_json::Value,
+    /// Cosine similarity to the query, 1.0 being identical
+    pub score: f32,
+}
+
+// Audio Types
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum AudioFormat {
+    #[default]
+    Mp3,
+    Opus,
+    Aac,
+    Flac,
+    Wav,
+}
+
+impl AudioFormat {
+    pub fn content_type(&self) -> &'static str {
+        match self {
+            AudioFormat::Mp3 => "audio/mpeg",
+            AudioFormat::Opus => "audio/ogg",
+            AudioFormat::Aac => "audio/aac",
+            AudioFormat::Flac => "audio/flac",
+            AudioFormat::Wav => "audio/wav",
+        }
+    }
+}
+
+/// Audio to transcribe. `filename` tells providers the audio's format.
+#[derive(Debug, Clone)]
+pub struct TranscriptionRequest {
+    pub audio: Vec<u8>,
+    pub filename: String,
+    /// ISO-639-1 code; detected when not given
+    pub language: Option<String>,
+    /// Text guiding the transcript's spelling and style
+    pub prompt: Option<String>,
+    pub model: Option<String>,
+    pub context: RequestContext,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionResult {
+    pub text: String,
+    pub language: Option<String>,
+    pub duration_seconds: f64,
+    pub segments: Vec<TranscriptSegment>,
+    pub model: String,
+    pub usage: TokenUsage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptSegment {
+    /// Offsets into the audio, in seconds
+    pub start: f64,
+    pub end: f64,
+    pub text: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SpeechSynthesisRequest {
+    pub text: String,
+    pub voice: Option<String>,
+    #[serde(default)]
+    pub format: AudioFormat,
+    /// 0.25 to 4.0, 1.0 being normal speed
+    pub speed: Option<f32>,
+    pub model: Option<String>,
+    pub context: RequestContext,
+}
+
+#[derive(Debug, Clone)]
+pub struct SynthesizedSpeech {
+    pub audio: Vec<u8>,
+    pub format: AudioFormat,
+    pub model: String,
+    pub usage: TokenUsage,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum TranscriptionJobStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+/// Transcription of an audio file stored in file-service. The transcript
+/// is indexed into the tenant's vector collection `collection`, one
+/// document per passage, so that it can be searched.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionJob {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub user_id: String,
+    pub workflow_id: Option<String>,
+    pub file_id: Uuid,
+    pub collection: String,
+    pub status: TranscriptionJobStatus,
+    pub transcript: Option<TranscriptionResult>,
+    /// Documents the transcript was indexed as
+    pub documents: i32,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Transcribe a file-service file as the job with id `job_id`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscribeFileRequest {
+    pub job_id: Uuid,
+    pub file_id: Uuid,
+    pub collection: Option<String>,
+    pub language: Option<String>,
+    pub model: Option<String>,
+    /// Serves both the transcription and the transcript's embeddings
+    pub provider: Option<AIProvider>,
+    pub context: RequestContext,
+}
+
+// Image Types
+/// A prompt to draw images from
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageGenerationRequest {
+    pub prompt: String,
+    /// e.g. "1024x1024"; the model's default when not given
+    pub size: Option<String>,
+    /// "standard" or "hd"
+    pub quality: Option<String>,
+    /// Images to draw; one when not given
+    pub count: Option<u32>,
+    pub model: Option<String>,
+    pub context: RequestContext,
+}
+
+/// Images as a provider returns them, before they are stored
+#[derive(Debug, Clone)]
+pub struct GeneratedImages {
+    pub images: Vec<GeneratedImage>,
+    pub model: String,
+    pub usage: TokenUsage,
+}
+
+#[derive(Debug, Clone)]
+pub struct GeneratedImage {
+    pub data: Vec<u8>,
+    pub mime_type: String,
+    /// The prompt the provider rewrote the request's prompt into, if it did
+    pub revised_prompt: Option<String>,
+}
+
+/// Generated images, stored in file-service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageGenerationResult {
+    pub images: Vec<StoredImage>,
+    pub model: String,
+    pub usage: TokenUsage,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StoredImage {
+    pub file_id: Uuid,
+    /// A scaled-down copy of the image; `None` when the image is already
+    /// small or could not be scaled
+    pub preview_file_id: Option<Uuid>,
+    pub revised_prompt: Option<String>,
+}
+
+/// A question about images stored in file-service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageAnalysisRequest {
+    pub file_ids: Vec<Uuid>,
+    pub prompt: String,
+    pub max_tokens: Option<u32>,
+    pub model: Option<String>,
+    pub context: RequestContext,
+}
+
+/// An image sent to a provider with an analysis request
+#[derive(Debug, Clone)]
+pub struct ImageInput {
+    pub data: Vec<u8>,
+    pub mime_type: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ImageAnalysisResult {
+    pub analysis: String,
+    pub model: String,
+    pub usage: TokenUsage,
+}
+
+// Prompt Template Types
+/// A named prompt, versioned per tenant. Versions are immutable; changing a
+/// template adds a version.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptTemplate {
+    pub id: Uuid,
+    /// `None` for the built-in templates every tenant can use
+    pub tenant_id: Option<String>,
+    pub name: String,
+    pub version: i32,
+    pub description: Option<String>,
+    /// Prompt text with `{{variable}}` placeholders
+    pub template: String,
+    pub variables: Vec<PromptVariable>,
+    /// Model to use unless the caller asks for one
+    pub model: Option<String>,
+    pub parameters: AIParameters,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptVariable {
+    pub name: String,
+    pub description: Option<String>,
+    /// Used when the variable is not given; variables without a default are required
+    #[serde(default)]
+    pub default: Option<String>,
+}
+
+/// A template by name, at `version` or the latest version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromptReference {
+    pub name: String,
+    #[serde(default)]
+    pub version: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderPromptRequest {
+    pub tenant_id: String,
+    pub template: PromptReference,
+    pub variables: HashMap<String, String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedPrompt {
+    pub name: String,
+    pub version: i32,
+    pub prompt: String,
+    pub model: Option<String>,
+    pub parameters: AIParameters,
+}
+
+// Moderation Types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModerationAction {
+    /// Reject the request
+    Block,
+    /// Replace the matched text and continue
+    Redact,
+    /// Continue, and report the finding
+    Flag,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PiiType {
+    Email,
+    Phone,
+    CreditCard,
+    Ssn,
+    IpAddress,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModerationStage {
+    /// Prompts and texts sent to a model
+    Input,
+    /// Text generated by a model
+    Output,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ModerationSource {
+    Provider,
+    Keyword,
+    Pii,
+    /// The tenant's data loss prevention rules
+    Dlp,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationFinding {
+    pub source: ModerationSource,
+    /// Provider category, matched keyword, PII type, or DLP rule
+    pub category: String,
+    pub action: ModerationAction,
+}
+
+/// A tenant's moderation rules. Provider findings cannot be redacted, so
+/// `provider_action` is `block` or `flag`.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModerationPolicy {
+    pub enabled: bool,
+    pub provider_check: bool,
+    pub provider_action: ModerationAction,
+    pub blocked_keywords: Vec<String>,
+    pub keyword_action: ModerationAction,
+    pub pii_types: Vec<PiiType>,
+    pub pii_action: ModerationAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModeratedText {
+    /// The text, with redactions applied
+    pub text: String,
+    pub findings: Vec<ModerationFinding>,
+}
+
+// Provider Routing Types
+/// Which providers may serve a tenant's requests. Requests for a model of
+/// another provider are served by the cheapest allowed model instead.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ProviderRoutingPolicy {
+    /// Serve the tenant only from self-hosted models, for tenants whose
+    /// data may not leave the platform's infrastructure
+    pub self_hosted_only: bool,
+    /// Providers the tenant allows; empty allows all
+    pub allowed_providers: Vec<AIProvider>,
+}
+
+impl ProviderRoutingPolicy {
+    pub fn allows(&self, provider: &AIProvider) -> bool {
+        (!self.self_hosted_only || provider.is_self_hosted())
+            && (self.allowed_providers.is_empty() || self.allowed_providers.contains(provider))
+    }
+}
+
+impl AIProvider {
+    /// Whether requests stay on the platform's own infrastructure
+    pub fn is_self_hosted(&self) -> bool {
+        matches!(self, AIProvider::Local)
+    }
+}
+
+// Response Cache Types
+/// How a tenant's completions are cached
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachePolicy {
+    pub enabled: bool,
+    /// How long a response is served from the cache
+    pub ttl_seconds: u64,
+    /// Also serve responses cached for prompts similar to the request's,
+    /// for example classifications of near-identical documents
+    pub semantic_matching: bool,
+    /// Cosine similarity from which a cached prompt counts as the same
+    pub similarity_threshold: f32,
+}
+
+/// Cached responses to drop; unset fields match every response
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct CacheInvalidation {
+    pub model: Option<String>,
+    pub capability: Option<AICapability>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CacheStats {
+    /// Responses that have not expired
+    pub entries: i64,
+    pub hits: i64,
+    /// Provider cost the hits would have incurred
+    pub saved_cost: f64,
+}
+
+// Batch Processing Types
+/// The documents of a batch: an explicit list, or the user's file-service
+/// files that match a filter
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchSource {
+    FileIds(Vec<Uuid>),
+    Filter(BatchFileFilter),
+}
+
+/// Unset fields match every file
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchFileFilter {
+    /// e.g. ["text/plain", "text/markdown"]
+    #[serde(default)]
+    pub mime_types: Vec<String>,
+    /// Case-insensitive substring of the filename
+    pub filename_contains: Option<String>,
+    pub created_after: Option<DateTime<Utc>>,
+    pub created_before: Option<DateTime<Utc>>,
+}
+
+/// What is done with every document of a batch; at least one is required
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BatchOperations {
+    /// Categories to classify documents into
+    pub classify: Option<Vec<String>>,
+    pub summarize: Option<BatchSummarization>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchSummarization {
+    pub max_length: Option<u32>,
+    pub style: Option<SummarizationStyle>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BatchJobStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub enum BatchItemStatus {
+    Pending,
+    Completed,
+    Failed,
+}
+
+/// A batch of documents processed by `batch_ai_processing_workflow`. Once
+/// it completes, its results are in a CSV report stored in file-service.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchJob {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub user_id: String,
+    pub workflow_id: Option<String>,
+    pub operations: BatchOperations,
+    pub model: String,
+    pub status: BatchJobStatus,
+    pub total_items: i32,
+    pub completed_items: i32,
+    pub failed_items: i32,
+    pub usage: TokenUsage,
+    pub report_file_id: Option<Uuid>,
+    pub error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The outcome for one document of a batch
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchItem {
+    pub file_id: Uuid,
+    pub filename: String,
+    pub status: BatchItemStatus,
+    pub classification: Option<String>,
+    pub confidence: Option<f32>,
+    pub summary: Option<String>,
+    pub error: Option<String>,
+}
+
+/// Create the batch with id `job_id`, or pick it up where it stopped
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PrepareBatchRequest {
+    pub job_id: Uuid,
+    pub source: BatchSource,
+    pub operations: BatchOperations,
+    pub model: Option<String>,
+    pub context: RequestContext,
+}
+
+/// A batch and the documents it has yet to process
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchPlan {
+    pub job: BatchJob,
+    pub pending: Vec<Uuid>,
+    /// Most documents to process at once
+    pub max_concurrency: usize,
+}
+
+/// Process one document of a batch and checkpoint its outcome
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessBatchItemRequest {
+    pub job_id: Uuid,
+    pub file_id: Uuid,
+    pub context: RequestContext,
+}
+
+/// Complete the batch with id `job_id` by storing its report, or mark it
+/// failed with `error`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FinishBatchRequest {
+    pub job_id: Uuid,
+    pub error: Option<String>,
+    pub context: RequestContext,
+}
+
+/// A processed document. `usage` is only set by the run that did the work,
+/// so usage is metered once.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProcessedBatchItem {
+    pub item: BatchItem,
+    pub model: String,
+    pub usage: Vec<(AICapability, TokenUsage)>,
+    /// The provider rate limited the document, so the batch should slow down
+    pub rate_limited: bool,
+}
+
+// Audit Log Types
+/// How a tenant's AI interactions are audited
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditPolicy {
+    pub enabled: bool,
+    pub store_prompts: bool,
+    pub store_responses: bool,
+    /// PII redacted from stored prompts and responses
+    #[serde(default)]
+    pub redacted_pii_types: Vec<PiiType>,
+    /// Days records are kept for
+    pub retention_days: u32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub enum AuditOutcome {
+    Success,
+    /// Moderation rejected the content
+    Blocked,
+    Failed,
+}
+
+/// An AI interaction to audit
+#[derive(Debug, Clone)]
+pub struct AuditEntry {
+    pub context: RequestContext,
+    pub capability: AICapability,
+    pub model: String,
+    pub prompt: String,
+    /// What was returned to the caller; for structured results, their JSON
+    pub response: Option<String>,
+    pub outcome: AuditOutcome,
+    pub error: Option<String>,
+    pub usage: Option<TokenUsage>,
+    /// Served from the response cache
+    pub cached: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AuditRecord {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub user_id: String,
+    pub workflow_id: Option<String>,
+    pub capability: String,
+    pub model: String,
+    pub prompt: Option<String>,
+    pub response: Option<String>,
+    /// PII matches redacted from the prompt and response
+    pub redactions: i32,
+    pub outcome: AuditOutcome,
+    pub error: Option<String>,
+    pub cached: bool,
+    pub usage: TokenUsage,
+    pub exported_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+}
+
+/// Filters of the audit log; unset fields match every record
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditQuery {
+    pub user_id: Option<String>,
+    pub capability: Option<AICapability>,
+    pub outcome: Option<AuditOutcome>,
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+// PII Detection Types
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PiiMode {
+    /// Replace PII with `[REDACTED]`
+    Redact,
+    /// Replace PII with tokens that are restored in generated text, so
+    /// answers referring to a value read back with it
+    Tokenize,
+}
+
+/// How a tenant's texts are cleared of PII before they leave the platform
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PiiPolicy {
+    pub enabled: bool,
+    /// PII found by patterns
+    #[serde(default)]
+    pub pii_types: Vec<PiiType>,
+    /// Entities found by the self-hosted NER model, e.g. `Person`
+    #[serde(default)]
+    pub entity_types: Vec<EntityType>,
+    pub mode: PiiMode,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PiiSource {
+    Pattern,
+    Ner,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PiiDetection {
+    pub source: PiiSource,
+    /// PII type or entity type
+    pub category: String,
+    pub text: String,
+    /// Byte offsets of the text
+    pub start: usize,
+    pub end: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RedactedText {
+    /// The text with its PII redacted or tokenized
+    pub text: String,
+    pub detections: Vec<PiiDetection>,
+}
+
 // Workflow-specific Types
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AIWorkflowRequest {
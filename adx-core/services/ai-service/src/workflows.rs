@@ -3,7 +3,8 @@ use crate::error::ActivityError;
 use crate::types::*;
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
-use crate::temporal_stubs::{WfContext, WorkflowResult, workflow};
+use crate::services::batch_jobs::next_concurrency;
+use crate::temporal::{ActivityOptions, ActivityStub, WfContext, WorkflowError, WorkflowResult};
 
 // User Onboarding AI Workflow
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -92,67 +93,57 @@ pub async fn user_onboarding_ai_workflow(
     ctx: WfContext,
     request: UserOnboardingAIRequest,
 ) -> WorkflowResult<UserOnboardingAIResult> {
-    let activities = ctx.activity(());
+    let activities = ctx.activity(ActivityOptions::generation());
+    
+    let context = RequestContext {
+        tenant_id: request.tenant_id.clone(),
+        user_id: request.user_id.clone(),
+        workflow_id: Some(ctx.workflow_info().workflow_id.clone()),
+        activity_id: None,
+        session_id: None,
+    };
+    let profile = &request.user_profile;
+    let preferences = &request.onboarding_preferences;
     
     // Step 1: Generate personalized welcome message
-    let welcome_prompt = format!(
-        "Create a personalized welcome message for a new user named {} with role {} in department {:?}. 
-        Their experience level is {:?} and they're interested in: {}. 
-        Communication style should be {:?}. Keep it warm, professional, and encouraging.",
-        request.user_profile.name,
-        request.user_profile.role,
-        request.user_profile.department,
-        request.user_profile.experience_level,
-        request.user_profile.interests.join(", "),
-        request.onboarding_preferences.communication_style
-    );
-    
-    let welcome_request = TextGenerationRequest {
-        prompt: welcome_prompt,
-        model: None, // Let the system choose the best model
-        parameters: AIParameters {
-            max_tokens: Some(300),
-            temperature: Some(0.7),
-            ..Default::default()
-        },
-        context: RequestContext {
-            tenant_id: request.tenant_id.clone(),
-            user_id: request.user_id.clone(),
-            workflow_id: Some(ctx.workflow_info().workflow_id.clone()),
+    let mut welcome_variables = HashMap::from([
+        ("name".to_string(), profile.name.clone()),
+        ("role".to_string(), profile.role.clone()),
+        ("experience_level".to_string(), format!("{:?}", profile.experience_level)),
+        ("interests".to_string(), profile.interests.join(", ")),
+        ("communication_style".to_string(), format!("{:?}", preferences.communication_style)),
+    ]);
+    if let Some(department) = &profile.department {
+        welcome_variables.insert("department".to_string(), department.clone());
+    }
+    
+    let welcome_request = templated_generation(
+        &activities,
+        "onboarding.welcome",
+        welcome_variables,
+        RequestContext {
             activity_id: Some("generate_welcome".to_string()),
-            session_id: None,
+            ..context.clone()
         },
-    };
+    ).await?;
     
     let welcome_result = activities.generate_text(welcome_request).await?;
     
     // Step 2: Generate feature recommendations based on user profile
-    let features_prompt = format!(
-        "Based on a user's profile - Role: {}, Experience: {:?}, Interests: {}, Goals: {} - 
-        recommend the top 5 most relevant features from our platform. 
-        Return as a JSON array of feature names with brief explanations.",
-        request.user_profile.role,
-        request.user_profile.experience_level,
-        request.user_profile.interests.join(", "),
-        request.onboarding_preferences.goals.join(", ")
-    );
-    
-    let features_request = TextGenerationRequest {
-        prompt: features_prompt,
-        model: None,
-        parameters: AIParameters {
-            max_tokens: Some(400),
-            temperature: Some(0.5),
-            ..Default::default()
-        },
-        context: RequestContext {
-            tenant_id: request.tenant_id.clone(),
-            user_id: request.user_id.clone(),
-            workflow_id: Some(ctx.workflow_info().workflow_id.clone()),
+    let features_request = templated_generation(
+        &activities,
+        "onboarding.features",
+        HashMap::from([
+            ("role".to_string(), profile.role.clone()),
+            ("experience_level".to_string(), format!("{:?}", profile.experience_level)),
+            ("interests".to_string(), profile.interests.join(", ")),
+            ("goals".to_string(), preferences.goals.join(", ")),
+        ]),
+        RequestContext {
             activity_id: Some("recommend_features".to_string()),
-            session_id: None,
+            ..context.clone()
         },
-    };
+    ).await?;
     
     let features_result = activities.generate_text(features_request).await?;
     
@@ -165,32 +156,20 @@ pub async fn user_onboarding_ai_workflow(
         .collect();
     
     // Step 3: Create personalized learning path
-    let learning_prompt = format!(
-        "Create a personalized learning path for a {} user with {:?} experience level. 
-        Their learning pace preference is {:?} and they want to achieve: {}. 
-        Create 4-6 learning steps with titles, descriptions, estimated time, and difficulty level.",
-        request.user_profile.role,
-        request.user_profile.experience_level,
-        request.onboarding_preferences.learning_pace,
-        request.onboarding_preferences.goals.join(", ")
-    );
-    
-    let learning_request = TextGenerationRequest {
-        prompt: learning_prompt,
-        model: None,
-        parameters: AIParameters {
-            max_tokens: Some(600),
-            temperature: Some(0.6),
-            ..Default::default()
-        },
-        context: RequestContext {
-            tenant_id: request.tenant_id.clone(),
-            user_id: request.user_id.clone(),
-            workflow_id: Some(ctx.workflow_info().workflow_id.clone()),
+    let learning_request = templated_generation(
+        &activities,
+        "onboarding.learning_path",
+        HashMap::from([
+            ("role".to_string(), profile.role.clone()),
+            ("experience_level".to_string(), format!("{:?}", profile.experience_level)),
+            ("learning_pace".to_string(), format!("{:?}", preferences.learning_pace)),
+            ("goals".to_string(), preferences.goals.join(", ")),
+        ]),
+        RequestContext {
             activity_id: Some("create_learning_path".to_string()),
-            session_id: None,
+            ..context.clone()
         },
-    };
+    ).await?;
     
     let learning_result = activities.generate_text(learning_request).await?;
     
@@ -198,31 +177,19 @@ pub async fn user_onboarding_ai_workflow(
     let learning_path = parse_learning_path(&learning_result.generated_text);
     
     // Step 4: Generate initial setup tasks
-    let setup_prompt = format!(
-        "Create a prioritized list of initial setup tasks for a new {} user. 
-        Consider their experience level ({:?}) and preferred features: {}. 
-        Include task titles, descriptions, priorities (High/Medium/Low), and estimated time.",
-        request.user_profile.role,
-        request.user_profile.experience_level,
-        request.onboarding_preferences.preferred_features.join(", ")
-    );
-    
-    let setup_request = TextGenerationRequest {
-        prompt: setup_prompt,
-        model: None,
-        parameters: AIParameters {
-            max_tokens: Some(500),
-            temperature: Some(0.4),
-            ..Default::default()
-        },
-        context: RequestContext {
-            tenant_id: request.tenant_id.clone(),
-            user_id: request.user_id.clone(),
-            workflow_id: Some(ctx.workflow_info().workflow_id.clone()),
+    let setup_request = templated_generation(
+        &activities,
+        "onboarding.setup_tasks",
+        HashMap::from([
+            ("role".to_string(), profile.role.clone()),
+            ("experience_level".to_string(), format!("{:?}", profile.experience_level)),
+            ("preferred_features".to_string(), preferences.preferred_features.join(", ")),
+        ]),
+        RequestContext {
             activity_id: Some("create_setup_tasks".to_string()),
-            session_id: None,
+            ..context.clone()
         },
-    };
+    ).await?;
     
     let setup_result = activities.generate_text(setup_request).await?;
     
@@ -312,7 +279,7 @@ pub async fn document_processing_ai_workflow(
     ctx: WfContext,
     request: DocumentProcessingAIRequest,
 ) -> WorkflowResult<DocumentProcessingAIResult> {
-    let activities = ctx.activity(());
+    let activities = ctx.activity(ActivityOptions::generation());
     let mut total_usage = TokenUsage {
         prompt_tokens: 0,
         completion_tokens: 0,
@@ -445,29 +412,18 @@ pub async fn document_processing_ai_workflow(
     
     // Step 4: Sentiment analysis if requested
     if request.processing_options.sentiment_analysis {
-        let sentiment_prompt = format!(
-            "Analyze the sentiment of the following {} document. 
-            Provide the overall sentiment (Positive, Negative, Neutral) and confidence score.
-            Also identify key emotions present (if any): joy, anger, fear, sadness, surprise, trust.
-            
-            Document: {}",
-            format!("{:?}", request.document_type).to_lowercase(),
-            request.document_content
-        );
-        
-        let sentiment_request = TextGenerationRequest {
-            prompt: sentiment_prompt,
-            model: None,
-            parameters: AIParameters {
-                max_tokens: Some(200),
-                temperature: Some(0.3),
-                ..Default::default()
-            },
-            context: RequestContext {
+        let sentiment_request = templated_generation(
+            &activities,
+            "documents.sentiment",
+            HashMap::from([
+                ("document_type".to_string(), format!("{:?}", request.document_type).to_lowercase()),
+                ("document".to_string(), request.document_content.clone()),
+            ]),
+            RequestContext {
                 activity_id: Some("analyze_sentiment".to_string()),
                 ..context.clone()
             },
-        };
+        ).await?;
         
         let sentiment_result = activities.generate_text(sentiment_request).await?;
         
@@ -579,68 +535,61 @@ pub async fn email_generation_ai_workflow(
     ctx: WfContext,
     request: EmailGenerationAIRequest,
 ) -> WorkflowResult<EmailGenerationAIResult> {
-    let activities = ctx.activity(());
+    let activities = ctx.activity(ActivityOptions::generation());
     
-    // Build comprehensive prompt for email generation
-    let mut prompt = format!(
-        "Generate a {} email with the following specifications:\n\n",
-        format!("{:?}", request.email_type).to_lowercase()
-    );
+    // Recipient and context details the email template lists
+    let mut details = String::new();
     
     // Add recipient information
     if let Some(name) = &request.recipient_info.name {
-        prompt.push_str(&format!("Recipient: {}\n", name));
+        details.push_str(&format!("Recipient: {}\n", name));
     }
     if let Some(role) = &request.recipient_info.role {
-        prompt.push_str(&format!("Recipient Role: {}\n", role));
+        details.push_str(&format!("Recipient Role: {}\n", role));
     }
     if let Some(company) = &request.recipient_info.company {
-        prompt.push_str(&format!("Company: {}\n", company));
+        details.push_str(&format!("Company: {}\n", company));
     }
     
     // Add email context
     if let Some(subject_hint) = &request.email_context.subject_hint {
-        prompt.push_str(&format!("Subject should relate to: {}\n", subject_hint));
+        details.push_str(&format!("Subject should relate to: {}\n", subject_hint));
     }
     
     if !request.email_context.key_points.is_empty() {
-        prompt.push_str(&format!("Key points to include:\n"));
+        details.push_str("Key points to include:\n");
         for point in &request.email_context.key_points {
-            prompt.push_str(&format!("- {}\n", point));
+            details.push_str(&format!("- {}\n", point));
         }
     }
     
     if let Some(cta) = &request.email_context.call_to_action {
-        prompt.push_str(&format!("Call to action: {}\n", cta));
+        details.push_str(&format!("Call to action: {}\n", cta));
     }
     
-    // Add generation options
-    prompt.push_str(&format!("\nTone: {:?}\n", request.generation_options.tone));
-    prompt.push_str(&format!("Length: {:?}\n", request.generation_options.length));
-    prompt.push_str(&format!("Personalization: {:?}\n", request.generation_options.personalization_level));
-    
-    prompt.push_str("\nGenerate both subject line and email body. Format as:\nSUBJECT: [subject line]\nBODY:\n[email body]");
-    
-    let generation_request = TextGenerationRequest {
-        prompt,
-        model: None,
-        parameters: AIParameters {
-            max_tokens: Some(match request.generation_options.length {
-                EmailLength::Brief => 300,
-                EmailLength::Medium => 600,
-                EmailLength::Detailed => 1000,
-            }),
-            temperature: Some(0.7),
-            ..Default::default()
-        },
-        context: RequestContext {
+    let mut generation_request = templated_generation(
+        &activities,
+        "email.generate",
+        HashMap::from([
+            ("email_type".to_string(), format!("{:?}", request.email_type).to_lowercase()),
+            ("details".to_string(), details),
+            ("tone".to_string(), format!("{:?}", request.generation_options.tone)),
+            ("length".to_string(), format!("{:?}", request.generation_options.length)),
+            ("personalization".to_string(), format!("{:?}", request.generation_options.personalization_level)),
+        ]),
+        RequestContext {
             tenant_id: request.tenant_id.clone(),
             user_id: request.user_id.clone(),
             workflow_id: Some(ctx.workflow_info().workflow_id.clone()),
             activity_id: Some("generate_email".to_string()),
             session_id: None,
         },
-    };
+    ).await?;
+    generation_request.parameters.max_tokens = Some(match request.generation_options.length {
+        EmailLength::Brief => 300,
+        EmailLength::Medium => 600,
+        EmailLength::Detailed => 1000,
+    });
     
     let generation_result = activities.generate_text(generation_request).await?;
     
@@ -662,6 +611,32 @@ pub async fn email_generation_ai_workflow(
     })
 }
 
+/// Generation request from the latest version of a prompt template. The
+/// template is rendered by an activity, so a replay uses the version the
+/// workflow originally ran with.
+async fn templated_generation(
+    activities: &ActivityStub,
+    template: &str,
+    variables: HashMap<String, String>,
+    context: RequestContext,
+) -> WorkflowResult<TextGenerationRequest> {
+    let rendered = activities.render_prompt(RenderPromptRequest {
+        tenant_id: context.tenant_id.clone(),
+        template: PromptReference {
+            name: template.to_string(),
+            version: None,
+        },
+        variables,
+    }).await?;
+    
+    Ok(TextGenerationRequest {
+        prompt: rendered.prompt,
+        model: rendered.model,
+        parameters: rendered.parameters,
+        context,
+    })
+}
+
 // Helper functions for parsing AI responses
 fn parse_learning_path(content: &str) -> Vec<LearningStep> {
     // Simplified parsing - in production, would use more sophisticated parsing
@@ -782,4 +757,173 @@ fn extract_personalization_elements(body: &str, recipient_info: &RecipientInfo)
     }
     
     elements
-}
\ No newline at end of file
+}
+// Streaming Generation AI Workflow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StreamingGenerationAIRequest {
+    pub tenant_id: String,
+    pub user_id: String,
+    /// Id clients follow the output under, chosen by the caller so that it
+    /// is known before the workflow runs
+    pub stream_id: uuid::Uuid,
+    pub prompt: String,
+    pub model: Option<String>,
+    pub parameters: AIParameters,
+}
+
+pub async fn streaming_generation_ai_workflow(
+    ctx: WfContext,
+    request: StreamingGenerationAIRequest,
+) -> WorkflowResult<StreamSession> {
+    let activities = ctx.activity(ActivityOptions::generation());
+    
+    let stream_request = StreamTextGenerationRequest {
+        stream_id: request.stream_id,
+        generation: TextGenerationRequest {
+            prompt: request.prompt,
+            model: request.model,
+            parameters: request.parameters,
+            context: RequestContext {
+                tenant_id: request.tenant_id,
+                user_id: request.user_id,
+                workflow_id: Some(ctx.workflow_info().workflow_id.clone()),
+                activity_id: Some("stream_text_generation".to_string()),
+                session_id: None,
+            },
+        },
+    };
+    
+    // Output is persisted as it is generated; clients follow it with
+    // GET /api/v1/generate/streams/:stream_id
+    let session = activities.stream_text_generation(stream_request).await?;
+    
+    Ok(session)
+}
+
+// Transcription AI Workflow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranscriptionAIRequest {
+    pub tenant_id: String,
+    pub user_id: String,
+    /// Id clients follow the job under, chosen by the caller so that it is
+    /// known before the workflow runs
+    pub job_id: uuid::Uuid,
+    /// Audio file stored in file-service
+    pub file_id: uuid::Uuid,
+    /// Vector collection the transcript is indexed into
+    pub collection: Option<String>,
+    pub language: Option<String>,
+    pub model: Option<String>,
+    pub provider: Option<AIProvider>,
+}
+
+pub async fn transcription_ai_workflow(
+    ctx: WfContext,
+    request: TranscriptionAIRequest,
+) -> WorkflowResult<TranscriptionJob> {
+    let activities = ctx.activity(ActivityOptions::generation());
+    
+    let transcribe_request = TranscribeFileRequest {
+        job_id: request.job_id,
+        file_id: request.file_id,
+        collection: request.collection,
+        language: request.language,
+        model: request.model,
+        provider: request.provider,
+        context: RequestContext {
+            tenant_id: request.tenant_id,
+            user_id: request.user_id,
+            workflow_id: Some(ctx.workflow_info().workflow_id.clone()),
+            activity_id: Some("transcribe_file".to_string()),
+            session_id: None,
+        },
+    };
+    
+    // Progress can be followed with GET /api/v1/audio/transcriptions/jobs/:job_id
+    let job = activities.transcribe_file(transcribe_request).await?;
+    
+    Ok(job)
+}
+
+// Batch AI Processing Workflow
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchAIProcessingRequest {
+    pub tenant_id: String,
+    pub user_id: String,
+    /// Id clients follow the batch under, chosen by the caller so that it
+    /// is known before the workflow runs
+    pub job_id: uuid::Uuid,
+    pub source: BatchSource,
+    pub operations: BatchOperations,
+    pub model: Option<String>,
+}
+
+pub async fn batch_ai_processing_workflow(
+    ctx: WfContext,
+    request: BatchAIProcessingRequest,
+) -> WorkflowResult<BatchJob> {
+    let activities = ctx.activity(ActivityOptions::generation());
+    let context = RequestContext {
+        tenant_id: request.tenant_id,
+        user_id: request.user_id,
+        workflow_id: Some(ctx.workflow_info().workflow_id.clone()),
+        activity_id: None,
+        session_id: None,
+    };
+    
+    // Documents are checkpointed as they are processed, so a retried
+    // workflow only processes those still pending
+    let plan = activities.prepare_batch_job(PrepareBatchRequest {
+        job_id: request.job_id,
+        source: request.source,
+        operations: request.operations,
+        model: request.model,
+        context: RequestContext {
+            activity_id: Some("prepare_batch_job".to_string()),
+            ..context.clone()
+        },
+    }).await?;
+    
+    // Fan out a wave of documents at a time, narrowing the waves while
+    // providers rate limit them
+    let mut concurrency = plan.max_concurrency;
+    let mut error = None;
+    let mut pending = plan.pending.into_iter().peekable();
+    while pending.peek().is_some() && error.is_none() {
+        let wave: Vec<uuid::Uuid> = pending.by_ref().take(concurrency).collect();
+        let processed = futures::future::join_all(wave.into_iter().map(|file_id| {
+            activities.process_batch_item(ProcessBatchItemRequest {
+                job_id: request.job_id,
+                file_id,
+                context: RequestContext {
+                    activity_id: Some(format!("process_batch_item:{}", file_id)),
+                    ..context.clone()
+                },
+            })
+        })).await;
+        
+        let mut rate_limited = false;
+        for result in processed {
+            match result {
+                Ok(item) => rate_limited |= item.rate_limited,
+                Err(e) => error = Some(e.to_string()),
+            }
+        }
+        concurrency = next_concurrency(concurrency, plan.max_concurrency, rate_limited);
+    }
+    
+    // Store the report, or record why the batch stopped
+    let job = activities.finish_batch_job(FinishBatchRequest {
+        job_id: request.job_id,
+        error: error.clone(),
+        context: RequestContext {
+            activity_id: Some("finish_batch_job".to_string()),
+            ..context
+        },
+    }).await?;
+    
+    match error {
+        Some(error) => Err(WorkflowError::ActivityFailed(error)),
+        None => Ok(job),
+    }
+}
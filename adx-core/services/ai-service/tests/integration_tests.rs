@@ -15,6 +15,12 @@ async fn test_ai_service_configuration() {
                 default_model: "gpt-3.5-turbo".to_string(),
                 max_tokens: 4096,
                 temperature: 0.7,
+                embedding_model: "text-embedding-3-small".to_string(),
+                transcription_model: "whisper-1".to_string(),
+                speech_model: "tts-1".to_string(),
+                speech_voice: "alloy".to_string(),
+                image_model: "dall-e-3".to_string(),
+                vision_model: "gpt-4o-mini".to_string(),
             },
             anthropic: ai_service::config::AnthropicConfig {
                 api_key: "test-key".to_string(),
@@ -26,6 +32,43 @@ async fn test_ai_service_configuration() {
                 enabled: false,
                 base_url: "http://localhost:11434".to_string(),
                 models: vec!["llama2-7b".to_string()],
+                embedding_model: "nomic-embed-text".to_string(),
+                model_tags: std::collections::HashMap::new(),
+                resources: ai_service::config::LocalResourceConfig {
+                    num_gpu: None,
+                    main_gpu: None,
+                    num_thread: None,
+                    num_ctx: None,
+                    keep_alive: "5m".to_string(),
+                },
+                speech_base_url: None,
+                transcription_model: "Systran/faster-whisper-small".to_string(),
+                speech_model: "tts-1".to_string(),
+                speech_voice: "alloy".to_string(),
+                vision_model: "llava".to_string(),
+            },
+            azure_openai: ai_service::config::AzureOpenAIConfig {
+                api_key: String::new(),
+                endpoint: String::new(),
+                api_version: "2024-06-01".to_string(),
+                default_model: "azure-gpt-35-turbo".to_string(),
+                deployments: std::collections::HashMap::new(),
+                max_tokens: 4096,
+            },
+            bedrock: ai_service::config::BedrockConfig {
+                region: "us-east-1".to_string(),
+                access_key_id: String::new(),
+                secret_access_key: String::new(),
+                session_token: None,
+                default_model: "anthropic.claude-3-haiku-20240307-v1:0".to_string(),
+                models: vec!["anthropic.claude-3-haiku-20240307-v1:0".to_string()],
+                max_tokens: 4096,
+            },
+            gemini: ai_service::config::GeminiConfig {
+                api_key: String::new(),
+                base_url: None,
+                default_model: "gemini-1.5-flash".to_string(),
+                max_tokens: 8192,
             },
         },
         monitoring: ai_service::config::MonitoringConfig {
@@ -39,6 +82,57 @@ async fn test_ai_service_configuration() {
             rate_limit_per_minute: 60,
             max_request_size: 1048576,
         },
+        budget: ai_service::config::BudgetConfig {
+            enabled: false,
+            license_service_url: "http://localhost:8087".to_string(),
+            over_budget_action: ai_service::config::OverBudgetAction::Reject,
+            cache_seconds: 300,
+        },
+        moderation: ai_service::config::ModerationConfig {
+            enabled: false,
+            provider_check: false,
+            blocked_keywords: vec![],
+            security_service_url: "http://localhost:8087".to_string(),
+        },
+        audio: ai_service::config::AudioConfig {
+            file_service_url: "http://localhost:8083".to_string(),
+            max_upload_bytes: 10485760,
+            max_file_bytes: 26214400,
+            transcript_collection: "transcripts".to_string(),
+        },
+        images: ai_service::config::ImageConfig {
+            file_service_url: "http://localhost:8083".to_string(),
+            max_file_bytes: 20971520,
+            max_images: 10,
+            preview_max_edge: 256,
+        },
+        cache: ai_service::config::CacheConfig {
+            enabled: false,
+            ttl_seconds: 86400,
+            semantic_matching: false,
+            similarity_threshold: 0.95,
+        },
+        batch: ai_service::config::BatchConfig {
+            file_service_url: "http://localhost:8083".to_string(),
+            max_items: 1000,
+            max_file_bytes: 1048576,
+            max_concurrency: 4,
+        },
+        audit: ai_service::config::AuditConfig {
+            enabled: true,
+            store_prompts: true,
+            store_responses: true,
+            redact_pii: true,
+            retention_days: 90,
+            purge_interval_seconds: 3600,
+        },
+        pii: ai_service::config::PiiConfig {
+            enabled: false,
+            detect_patterns: true,
+            ner_model: "llama2-7b".to_string(),
+            token_ttl_seconds: 604800,
+            purge_interval_seconds: 3600,
+        },
     };
     
     // Verify configuration is valid
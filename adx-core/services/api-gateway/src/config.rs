@@ -1,4 +1,5 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::time::Duration;
 use anyhow::{Result, Context};
 
@@ -10,6 +11,21 @@ pub struct ApiGatewayConfig {
     pub auth: AuthConfig,
     pub rate_limiting: RateLimitingConfig,
     pub redis: RedisConfig,
+    pub licensing: LicensingConfig,
+    pub network_policy: NetworkPolicyConfig,
+    pub custom_domains: CustomDomainConfig,
+    pub openapi: OpenApiConfig,
+    pub graphql: GraphqlConfig,
+    pub websocket: WebSocketConfig,
+    pub upstreams: UpstreamConfig,
+    pub idempotency: IdempotencyConfig,
+    pub transforms: TransformConfig,
+    pub grpc: GrpcConfig,
+    pub validation: ValidationConfig,
+    pub webhooks: WebhookConfig,
+    pub mirror: MirrorConfig,
+    pub discovery: DiscoveryConfig,
+    pub maintenance: MaintenanceConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -36,6 +52,10 @@ pub struct ServicesConfig {
     pub tenant_service: ServiceEndpoint,
     pub file_service: ServiceEndpoint,
     pub workflow_service: ServiceEndpoint,
+    pub module_service: ServiceEndpoint,
+    pub license_service: ServiceEndpoint,
+    pub security_service: ServiceEndpoint,
+    pub white_label_service: ServiceEndpoint,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -44,19 +64,295 @@ pub struct ServiceEndpoint {
     pub timeout_seconds: u64,
 }
 
+impl ServicesConfig {
+    /// Every service's endpoint, under the name the gateway routes it by
+    pub fn endpoints(&self) -> [(&'static str, &ServiceEndpoint); 9] {
+        [
+            ("auth", &self.auth_service),
+            ("user", &self.user_service),
+            ("tenant", &self.tenant_service),
+            ("file", &self.file_service),
+            ("workflow", &self.workflow_service),
+            ("module", &self.module_service),
+            ("license", &self.license_service),
+            ("security", &self.security_service),
+            ("white_label", &self.white_label_service),
+        ]
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct AuthConfig {
     pub jwt_secret: String,
     pub jwt_expiration_hours: u64,
     pub require_auth: bool,
+    /// Validate tokens against security-service's rotating signing keys;
+    /// `jwt_secret` still covers tokens issued without a key id
+    pub rotating_signing_keys: bool,
+    /// How long the signing key set is cached
+    pub signing_key_cache_seconds: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RateLimitingConfig {
     pub enabled: bool,
+    /// Defaults per user and endpoint, and for tenants whose plan doesn't
+    /// set its own
     pub requests_per_minute: u32,
     pub requests_per_hour: u32,
     pub burst_limit: u32,
+    /// How long a started workflow counts against the tenant's concurrent
+    /// workflow limit unless it's seen finishing sooner
+    #[serde(default)]
+    pub workflow_slot_seconds: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LicensingConfig {
+    /// Hold tenants to the access mode of their entitlements: read-only
+    /// during a lapsed license's grace period, suspended after it
+    pub enforce_access_mode: bool,
+    /// How long a tenant's entitlements are cached
+    pub entitlement_cache_seconds: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NetworkPolicyConfig {
+    /// Refuse requests the tenant's network policy does not allow
+    pub enforce: bool,
+    /// How long a tenant's network policy is cached
+    pub cache_seconds: u64,
+    /// Header the edge puts the client's ISO country code in
+    pub country_header: String,
+    /// Take the client address from X-Forwarded-For, as appended by the
+    /// proxies in front of the gateway
+    pub trust_forwarded_for: bool,
+    /// Proxies in front of the gateway that append to X-Forwarded-For; the
+    /// client address is the entry the outermost of them added, and
+    /// anything left of it was sent by the client
+    pub trusted_proxies: usize,
+    /// Let requests through when a tenant's policy can't be fetched and
+    /// none is cached, rather than refusing them
+    pub fail_open: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomDomainConfig {
+    /// Serve tenants' verified custom domains and pin each to its tenant
+    pub enabled: bool,
+    /// How long domain routes and certificates are cached
+    pub cache_seconds: u64,
+    /// Token white-label-service requires to hand out domain routes
+    pub internal_token: String,
+    /// Port to terminate TLS on, choosing each domain's certificate by SNI;
+    /// None when TLS is terminated in front of the gateway
+    pub tls_port: Option<u16>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenApiConfig {
+    /// How long the combined OpenAPI document is cached before services'
+    /// contributions are fetched again
+    pub cache_seconds: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphqlConfig {
+    /// Serve the read-only GraphQL endpoint at `/graphql`
+    pub enabled: bool,
+    /// Deepest nesting a query may have
+    pub max_depth: usize,
+    /// Most fields a query may resolve
+    pub max_complexity: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebSocketConfig {
+    /// Serve subscriptions over WebSocket at `/api/v1/ws`
+    pub enabled: bool,
+    /// Most subscriptions one connection may hold open
+    pub max_subscriptions: usize,
+    /// How often subscribed workflows' status is checked
+    pub workflow_poll_seconds: u64,
+    /// How often idle connections are pinged
+    pub heartbeat_seconds: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpstreamConfig {
+    /// Consecutive failures that open a service's circuit
+    pub failure_threshold: u32,
+    /// How long an open circuit refuses requests before trial requests are
+    /// let through
+    pub open_seconds: u64,
+    /// Trial requests let through at once while a circuit is half-open
+    pub half_open_requests: u32,
+    /// Requests in flight to one service at most
+    pub max_concurrent_requests: usize,
+    /// How long a request waits for a free slot before it's refused
+    pub queue_timeout_ms: u64,
+    /// Limits on requests in flight for particular services
+    #[serde(default)]
+    pub concurrency_overrides: HashMap<String, usize>,
+    /// Responses served in place of an error while a service is unavailable
+    #[serde(default)]
+    pub fallbacks: HashMap<String, FallbackResponse>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FallbackResponse {
+    pub status: u16,
+    pub body: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IdempotencyConfig {
+    /// Replay responses for repeated `Idempotency-Key` headers
+    pub enabled: bool,
+    /// How long a completed request's response is kept for replay
+    pub ttl_seconds: u64,
+    /// How long a key stays claimed by a request that hasn't finished,
+    /// e.g. because the gateway instance handling it went away
+    pub lock_seconds: u64,
+    /// Larger responses aren't stored, and their keys can be used again
+    pub max_response_bytes: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransformConfig {
+    /// JSON file of per-route request and response transformations; none
+    /// are applied without one
+    pub policy_file: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GrpcConfig {
+    /// Pass `application/grpc` requests through to the services' gRPC APIs
+    pub enabled: bool,
+    pub timeout_seconds: u64,
+    /// Where each protobuf package is served, e.g. `adx.quota`
+    #[serde(default)]
+    pub routes: HashMap<String, GrpcRoute>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GrpcRoute {
+    /// Upstream whose circuit breaker guards the calls
+    pub service: String,
+    pub endpoint: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ValidationConfig {
+    /// Refuse requests that don't match the combined OpenAPI document
+    pub enabled: bool,
+    /// Most field errors listed in one response
+    pub max_errors: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookConfig {
+    /// JSON file of inbound webhook integrations; no webhook endpoints are
+    /// served without one
+    pub integrations_file: Option<String>,
+    /// How long a handled delivery is remembered, so the sender's repeats
+    /// are acknowledged without being dispatched again
+    pub dedupe_seconds: u64,
+    /// How long payloads are kept for inspection and replay
+    pub archive_seconds: u64,
+    /// How long a delivery stays claimed by a request that hasn't finished
+    pub lock_seconds: u64,
+    pub max_body_bytes: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MirrorConfig {
+    /// Shadow backends by service name; requests to other services aren't
+    /// mirrored
+    #[serde(default)]
+    pub targets: HashMap<String, MirrorTarget>,
+    /// Shadow calls in flight at most; sampled requests beyond it are skipped
+    pub max_concurrent: usize,
+    pub timeout_seconds: u64,
+    /// Mismatched responses kept per service for inspection
+    pub max_diffs: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct MirrorTarget {
+    pub base_url: String,
+    /// Share of the service's requests mirrored, from 0 to 100
+    pub sample_percent: f64,
+    /// Methods mirrored; reads only, unless the target has data of its own
+    /// to write to
+    #[serde(default = "default_mirror_methods")]
+    pub methods: Vec<String>,
+    /// Dotted response fields expected to differ, e.g. `meta.request_id`
+    #[serde(default)]
+    pub ignore_fields: Vec<String>,
+}
+
+fn default_mirror_methods() -> Vec<String> {
+    vec!["GET".to_string(), "HEAD".to_string()]
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum DiscoveryBackend {
+    /// The `services` URLs, plus any endpoints in the endpoints file
+    Static,
+    /// A and AAAA records of each service's name
+    Dns,
+    /// Passing instances in the Consul catalog
+    Consul,
+    /// Ready addresses of a Kubernetes Service's Endpoints
+    Kubernetes,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DiscoveryConfig {
+    pub backend: DiscoveryBackend,
+    /// JSON file of endpoint URLs by service for the static backend,
+    /// re-read when it changes
+    pub endpoints_file: Option<String>,
+    /// How each service is registered with the DNS, Consul or Kubernetes
+    /// backend; services not listed stay on their configured URL
+    #[serde(default)]
+    pub services: HashMap<String, DiscoveredService>,
+    pub consul_url: String,
+    pub kubernetes_namespace: String,
+    /// How often endpoints are looked up again
+    pub refresh_seconds: u64,
+    /// How often every endpoint's health is checked; 0 turns checks off
+    pub health_check_seconds: u64,
+    pub health_check_path: String,
+    pub health_check_timeout_ms: u64,
+    /// Failed checks in a row before an endpoint stops getting requests
+    pub unhealthy_threshold: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct DiscoveredService {
+    /// DNS name, Consul service name or Kubernetes Service name
+    pub name: String,
+    /// Port requests go to; required for DNS, otherwise the registered port
+    /// is used
+    pub port: Option<u16>,
+    #[serde(default = "default_discovery_scheme")]
+    pub scheme: String,
+}
+
+fn default_discovery_scheme() -> String {
+    "http".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MaintenanceConfig {
+    pub enabled: bool,
+    /// How often windows and kill switches are re-read from Redis
+    pub refresh_seconds: u64,
+    /// How long a domain's branding is reused on the maintenance page
+    pub branding_cache_seconds: u64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -119,23 +415,144 @@ impl ApiGatewayConfig {
                     base_url: "http://localhost:8084".to_string(),
                     timeout_seconds: 60, // Longer timeout for workflow operations
                 },
+                module_service: ServiceEndpoint {
+                    base_url: "http://localhost:8086".to_string(),
+                    timeout_seconds: 30,
+                },
+                license_service: ServiceEndpoint {
+                    base_url: "http://localhost:8087".to_string(),
+                    timeout_seconds: 10,
+                },
+                security_service: ServiceEndpoint {
+                    base_url: "http://localhost:8088".to_string(),
+                    timeout_seconds: 10,
+                },
+                white_label_service: ServiceEndpoint {
+                    base_url: "http://localhost:8089".to_string(),
+                    timeout_seconds: 30,
+                },
             },
             auth: AuthConfig {
                 jwt_secret: "development-secret-key-change-in-production".to_string(),
                 jwt_expiration_hours: 24,
                 require_auth: true,
+                rotating_signing_keys: false,
+                signing_key_cache_seconds: 60,
             },
             rate_limiting: RateLimitingConfig {
                 enabled: true,
                 requests_per_minute: 100,
                 requests_per_hour: 1000,
                 burst_limit: 20,
+                workflow_slot_seconds: 900,
             },
             redis: RedisConfig {
                 url: "redis://localhost:6379".to_string(),
                 pool_size: 10,
                 connection_timeout_seconds: 5,
             },
+            licensing: LicensingConfig {
+                enforce_access_mode: true,
+                entitlement_cache_seconds: 60,
+            },
+            network_policy: NetworkPolicyConfig {
+                enforce: true,
+                cache_seconds: 60,
+                country_header: "CF-IPCountry".to_string(),
+                trust_forwarded_for: false,
+                trusted_proxies: 1,
+                fail_open: false,
+            },
+            custom_domains: CustomDomainConfig {
+                enabled: false,
+                cache_seconds: 60,
+                internal_token: String::new(),
+                tls_port: None,
+            },
+            openapi: OpenApiConfig {
+                cache_seconds: 60,
+            },
+            graphql: GraphqlConfig {
+                enabled: false,
+                max_depth: 10,
+                max_complexity: 500,
+            },
+            websocket: WebSocketConfig {
+                enabled: true,
+                max_subscriptions: 20,
+                workflow_poll_seconds: 2,
+                heartbeat_seconds: 30,
+            },
+            upstreams: UpstreamConfig {
+                failure_threshold: 5,
+                open_seconds: 30,
+                half_open_requests: 1,
+                max_concurrent_requests: 200,
+                queue_timeout_ms: 100,
+                concurrency_overrides: HashMap::new(),
+                fallbacks: HashMap::new(),
+            },
+            idempotency: IdempotencyConfig {
+                enabled: true,
+                ttl_seconds: 86400,
+                lock_seconds: 60,
+                max_response_bytes: 1024 * 1024,
+            },
+            transforms: TransformConfig { policy_file: None },
+            grpc: GrpcConfig {
+                enabled: true,
+                timeout_seconds: 10,
+                routes: HashMap::from([
+                    (
+                        "adx.quota".to_string(),
+                        GrpcRoute {
+                            service: "license".to_string(),
+                            endpoint: "http://localhost:50087".to_string(),
+                        },
+                    ),
+                    (
+                        "adx.tenant".to_string(),
+                        GrpcRoute {
+                            service: "tenant".to_string(),
+                            endpoint: "http://localhost:50085".to_string(),
+                        },
+                    ),
+                ]),
+            },
+            validation: ValidationConfig {
+                enabled: true,
+                max_errors: 20,
+            },
+            webhooks: WebhookConfig {
+                integrations_file: None,
+                dedupe_seconds: 7 * 86400,
+                archive_seconds: 30 * 86400,
+                lock_seconds: 60,
+                max_body_bytes: 1024 * 1024,
+            },
+            mirror: MirrorConfig {
+                targets: HashMap::new(),
+                max_concurrent: 50,
+                timeout_seconds: 10,
+                max_diffs: 100,
+            },
+            discovery: DiscoveryConfig {
+                backend: DiscoveryBackend::Static,
+                endpoints_file: None,
+                services: HashMap::new(),
+                consul_url: "http://localhost:8500".to_string(),
+                kubernetes_namespace: "adx-core".to_string(),
+                refresh_seconds: 30,
+                health_check_seconds: 10,
+                health_check_path: "/health".to_string(),
+                health_check_timeout_ms: 2000,
+                unhealthy_threshold: 3,
+            },
+            maintenance: MaintenanceConfig {
+                enabled: true,
+                refresh_seconds: 5,
+                branding_cache_seconds: 300,
+            },
         }
     }
 
@@ -153,6 +570,99 @@ impl ApiGatewayConfig {
         if self.temporal.namespace.is_empty() {
             self.temporal.namespace = "adx-core-development".to_string();
         }
+        if self.network_policy.trusted_proxies == 0 {
+            self.network_policy.trusted_proxies = 1;
+        }
+        if self.network_policy.country_header.is_empty() {
+            self.network_policy.country_header = "CF-IPCountry".to_string();
+        }
+        if self.graphql.max_depth == 0 {
+            self.graphql.max_depth = 10;
+        }
+        if self.graphql.max_complexity == 0 {
+            self.graphql.max_complexity = 500;
+        }
+        if self.websocket.max_subscriptions == 0 {
+            self.websocket.max_subscriptions = 20;
+        }
+        if self.websocket.workflow_poll_seconds == 0 {
+            self.websocket.workflow_poll_seconds = 2;
+        }
+        if self.websocket.heartbeat_seconds == 0 {
+            self.websocket.heartbeat_seconds = 30;
+        }
+        if self.rate_limiting.workflow_slot_seconds == 0 {
+            self.rate_limiting.workflow_slot_seconds = 900;
+        }
+        if self.upstreams.failure_threshold == 0 {
+            self.upstreams.failure_threshold = 5;
+        }
+        if self.upstreams.open_seconds == 0 {
+            self.upstreams.open_seconds = 30;
+        }
+        if self.upstreams.half_open_requests == 0 {
+            self.upstreams.half_open_requests = 1;
+        }
+        if self.upstreams.max_concurrent_requests == 0 {
+            self.upstreams.max_concurrent_requests = 200;
+        }
+        if self.idempotency.ttl_seconds == 0 {
+            self.idempotency.ttl_seconds = 86400;
+        }
+        if self.idempotency.lock_seconds == 0 {
+            self.idempotency.lock_seconds = 60;
+        }
+        if self.idempotency.max_response_bytes == 0 {
+            self.idempotency.max_response_bytes = 1024 * 1024;
+        }
+        if self.grpc.timeout_seconds == 0 {
+            self.grpc.timeout_seconds = 10;
+        }
+        if self.validation.max_errors == 0 {
+            self.validation.max_errors = 20;
+        }
+        if self.webhooks.dedupe_seconds == 0 {
+            self.webhooks.dedupe_seconds = 7 * 86400;
+        }
+        if self.webhooks.archive_seconds == 0 {
+            self.webhooks.archive_seconds = 30 * 86400;
+        }
+        if self.webhooks.lock_seconds == 0 {
+            self.webhooks.lock_seconds = 60;
+        }
+        if self.webhooks.max_body_bytes == 0 {
+            self.webhooks.max_body_bytes = 1024 * 1024;
+        }
+        if self.mirror.max_concurrent == 0 {
+            self.mirror.max_concurrent = 50;
+        }
+        if self.mirror.timeout_seconds == 0 {
+            self.mirror.timeout_seconds = 10;
+        }
+        if self.discovery.consul_url.is_empty() {
+            self.discovery.consul_url = "http://localhost:8500".to_string();
+        }
+        if self.discovery.kubernetes_namespace.is_empty() {
+            self.discovery.kubernetes_namespace = "adx-core".to_string();
+        }
+        if self.discovery.refresh_seconds == 0 {
+            self.discovery.refresh_seconds = 30;
+        }
+        if self.discovery.health_check_path.is_empty() {
+            self.discovery.health_check_path = "/health".to_string();
+        }
+        if self.discovery.health_check_timeout_ms == 0 {
+            self.discovery.health_check_timeout_ms = 2000;
+        }
+        if self.discovery.unhealthy_threshold == 0 {
+            self.discovery.unhealthy_threshold = 3;
+        }
+        if self.maintenance.refresh_seconds == 0 {
+            self.maintenance.refresh_seconds = 5;
+        }
+        if self.maintenance.branding_cache_seconds == 0 {
+            self.maintenance.branding_cache_seconds = 300;
+        }
         if self.auth.jwt_secret.is_empty() {
             self.auth.jwt_secret = "development-secret-key-change-in-production".to_string();
         }
@@ -177,6 +687,10 @@ impl ApiGatewayConfig {
             "tenant" => self.services.tenant_service.timeout_seconds,
             "file" => self.services.file_service.timeout_seconds,
             "workflow" => self.services.workflow_service.timeout_seconds,
+            "module" => self.services.module_service.timeout_seconds,
+            "license" => self.services.license_service.timeout_seconds,
+            "security" => self.services.security_service.timeout_seconds,
+            "white_label" => self.services.white_label_service.timeout_seconds,
             _ => 10, // Default timeout
         };
         Duration::from_secs(timeout_seconds)
@@ -0,0 +1,665 @@
+//! Service discovery for the routed services.
+//!
+//! Every service has a pool of endpoints, filled by the configured backend:
+//! the `services` URLs and the endpoints file, DNS records, the Consul
+//! catalog or a Kubernetes Service's Endpoints. Pools are looked up again in
+//! the background, so instances coming and going, or an edited endpoints
+//! file, change the routing table without a restart. Endpoints are health
+//! checked and requests go round-robin to the healthy ones; when none pass,
+//! requests are spread over all of them and the service's circuit breaker
+//! has the last word.
+//!
+//! ```json
+//! {
+//!   "user": ["http://user-service-1:8082", "http://user-service-2:8082"]
+//! }
+//! ```
+
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU32, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, SystemTime};
+use tokio::task::JoinHandle;
+use tokio::time::Interval;
+use tracing::{debug, info, warn};
+
+use crate::config::{DiscoveredService, DiscoveryBackend, DiscoveryConfig, ServicesConfig};
+use crate::error::{ApiGatewayError, ApiResult};
+
+const KUBERNETES_TOKEN_PATH: &str = "/var/run/secrets/kubernetes.io/serviceaccount/token";
+const KUBERNETES_CA_PATH: &str = "/var/run/secrets/kubernetes.io/serviceaccount/ca.crt";
+
+#[derive(Debug)]
+struct Endpoint {
+    url: String,
+    consecutive_failures: AtomicU32,
+}
+
+#[derive(Debug)]
+struct EndpointPool {
+    /// Sorted by URL, so lookups returning the same endpoints in another
+    /// order don't count as a change
+    endpoints: Vec<Arc<Endpoint>>,
+    next: AtomicUsize,
+}
+
+impl EndpointPool {
+    /// Endpoints also in the previous pool keep their health
+    fn new(urls: Vec<String>, previous: Option<&EndpointPool>) -> Self {
+        let endpoints = urls
+            .into_iter()
+            .map(|url| {
+                previous
+                    .and_then(|pool| pool.endpoints.iter().find(|endpoint| endpoint.url == url))
+                    .cloned()
+                    .unwrap_or_else(|| {
+                        Arc::new(Endpoint {
+                            url,
+                            consecutive_failures: AtomicU32::new(0),
+                        })
+                    })
+            })
+            .collect();
+        Self {
+            endpoints,
+            next: AtomicUsize::new(0),
+        }
+    }
+
+    fn urls(&self) -> Vec<&str> {
+        self.endpoints.iter().map(|endpoint| endpoint.url.as_str()).collect()
+    }
+
+    /// Next endpoint in turn, among the healthy ones if there are any
+    fn next_endpoint(&self, unhealthy_threshold: u32) -> Option<&str> {
+        let healthy: Vec<&Arc<Endpoint>> = self
+            .endpoints
+            .iter()
+            .filter(|endpoint| endpoint.consecutive_failures.load(Ordering::Relaxed) < unhealthy_threshold)
+            .collect();
+        let candidates = if healthy.is_empty() {
+            self.endpoints.iter().collect()
+        } else {
+            healthy
+        };
+        if candidates.is_empty() {
+            return None;
+        }
+        let turn = self.next.fetch_add(1, Ordering::Relaxed);
+        Some(candidates[turn % candidates.len()].url.as_str())
+    }
+}
+
+/// A service's endpoints as reported by `/gateway/discovery`
+#[derive(Debug, Serialize)]
+pub struct ServicePool {
+    pub service: String,
+    pub endpoints: Vec<EndpointStatus>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct EndpointStatus {
+    pub url: String,
+    pub healthy: bool,
+    pub consecutive_failures: u32,
+}
+
+/// Outcome of looking every service up again
+#[derive(Debug, Serialize)]
+pub struct DiscoveryRefresh {
+    pub backend: DiscoveryBackend,
+    /// Services whose endpoints changed
+    pub changed: Vec<String>,
+    /// Services, or the endpoints file, whose lookup failed; their last
+    /// known endpoints are kept
+    pub failed: Vec<String>,
+    pub refreshed_at: DateTime<Utc>,
+}
+
+pub struct ServiceDiscovery {
+    config: DiscoveryConfig,
+    /// Each service's `services` URL, its only endpoint unless discovered
+    /// otherwise
+    configured: HashMap<String, String>,
+    pools: RwLock<HashMap<String, Arc<EndpointPool>>>,
+    http_client: reqwest::Client,
+    /// When the endpoints file was modified as of its last read
+    endpoints_file_modified: Mutex<Option<SystemTime>>,
+}
+
+impl ServiceDiscovery {
+    pub fn new(config: &DiscoveryConfig, services: &ServicesConfig) -> ApiResult<Self> {
+        if config.backend == DiscoveryBackend::Dns {
+            if let Some((service, _)) = config.services.iter().find(|(_, registration)| registration.port.is_none()) {
+                return Err(ApiGatewayError::ConfigurationError {
+                    message: format!("Service {} needs a port to be discovered through DNS", service),
+                });
+            }
+        }
+
+        let mut client = reqwest::Client::builder().timeout(Duration::from_secs(10));
+        if config.backend == DiscoveryBackend::Kubernetes {
+            let certificate = std::fs::read(KUBERNETES_CA_PATH)
+                .map_err(|e| e.to_string())
+                .and_then(|pem| reqwest::Certificate::from_pem(&pem).map_err(|e| e.to_string()))
+                .map_err(|e| ApiGatewayError::ConfigurationError {
+                    message: format!("Failed to load the Kubernetes CA from {}: {}", KUBERNETES_CA_PATH, e),
+                })?;
+            client = client.add_root_certificate(certificate);
+        }
+        let http_client = client.build().map_err(|e| ApiGatewayError::ConfigurationError {
+            message: format!("Failed to create discovery HTTP client: {}", e),
+        })?;
+
+        let configured: HashMap<String, String> = services
+            .endpoints()
+            .iter()
+            .map(|(service, endpoint)| (service.to_string(), normalize_url(&endpoint.base_url)))
+            .collect();
+        let pools = configured
+            .iter()
+            .map(|(service, url)| (service.clone(), Arc::new(EndpointPool::new(vec![url.clone()], None))))
+            .collect();
+
+        let discovery = Self {
+            config: config.clone(),
+            configured,
+            pools: RwLock::new(pools),
+            http_client,
+            endpoints_file_modified: Mutex::new(None),
+        };
+        // A broken endpoints file stops the gateway from starting; later
+        // edits that break it are logged and the last good pools kept
+        if config.backend == DiscoveryBackend::Static {
+            discovery.reload_endpoints_file()?;
+        }
+        Ok(discovery)
+    }
+
+    /// Base URL for the next request to a service. Services discovery
+    /// doesn't know keep the router's own URL.
+    pub fn resolve(&self, service: &str) -> ApiResult<Option<String>> {
+        let Some(pool) = self.pool(service) else {
+            return Ok(None);
+        };
+        pool.next_endpoint(self.config.unhealthy_threshold)
+            .map(|url| Some(url.to_string()))
+            .ok_or_else(|| ApiGatewayError::ServiceUnavailable {
+                service: service.to_string(),
+            })
+    }
+
+    fn pool(&self, service: &str) -> Option<Arc<EndpointPool>> {
+        self.pools.read().unwrap_or_else(|e| e.into_inner()).get(service).cloned()
+    }
+
+    /// Looks every discovered service up again
+    pub async fn refresh(&self) -> DiscoveryRefresh {
+        let mut changed = Vec::new();
+        let mut failed = Vec::new();
+
+        if self.config.backend == DiscoveryBackend::Static {
+            match self.reload_endpoints_file() {
+                Ok(services) => changed = services,
+                Err(e) => {
+                    warn!(error = %e, "Endpoints file reload failed, keeping the current endpoints");
+                    failed.extend(self.config.endpoints_file.clone());
+                }
+            }
+        } else {
+            for (service, registration) in &self.config.services {
+                match self.lookup(registration).await {
+                    Ok(urls) => {
+                        if self.replace_pool(service, urls) {
+                            changed.push(service.clone());
+                        }
+                    }
+                    Err(e) => {
+                        warn!(service = %service, error = %e, "Service lookup failed, keeping its last known endpoints");
+                        failed.push(service.clone());
+                    }
+                }
+            }
+        }
+
+        changed.sort();
+        failed.sort();
+        DiscoveryRefresh {
+            backend: self.config.backend,
+            changed,
+            failed,
+            refreshed_at: Utc::now(),
+        }
+    }
+
+    /// Reads the endpoints file if it changed since it was last read.
+    /// Services it doesn't list go back to their configured URL.
+    fn reload_endpoints_file(&self) -> ApiResult<Vec<String>> {
+        let Some(path) = &self.config.endpoints_file else {
+            return Ok(Vec::new());
+        };
+        let modified = std::fs::metadata(path)
+            .and_then(|metadata| metadata.modified())
+            .map_err(|e| ApiGatewayError::ConfigurationError {
+                message: format!("Failed to read service endpoints from {}: {}", path, e),
+            })?;
+        let mut last_modified = self.endpoints_file_modified.lock().unwrap_or_else(|e| e.into_inner());
+        if *last_modified == Some(modified) {
+            return Ok(Vec::new());
+        }
+
+        let contents = std::fs::read_to_string(path).map_err(|e| ApiGatewayError::ConfigurationError {
+            message: format!("Failed to read service endpoints from {}: {}", path, e),
+        })?;
+        let listed: HashMap<String, Vec<String>> =
+            serde_json::from_str(&contents).map_err(|e| ApiGatewayError::ConfigurationError {
+                message: format!("Invalid service endpoints in {}: {}", path, e),
+            })?;
+
+        let mut endpoints: HashMap<String, Vec<String>> = self
+            .configured
+            .iter()
+            .map(|(service, url)| (service.clone(), vec![url.clone()]))
+            .collect();
+        endpoints.extend(listed);
+
+        let mut changed: Vec<String> = endpoints
+            .into_iter()
+            .filter(|(service, urls)| self.replace_pool(service, urls.clone()))
+            .map(|(service, _)| service)
+            .collect();
+        changed.sort();
+        *last_modified = Some(modified);
+        info!(path = %path, changed = ?changed, "Service endpoints file loaded");
+        Ok(changed)
+    }
+
+    /// Swaps in a service's endpoints, reporting whether they changed
+    fn replace_pool(&self, service: &str, urls: Vec<String>) -> bool {
+        let mut urls: Vec<String> = urls.iter().map(|url| normalize_url(url)).collect();
+        urls.sort();
+        urls.dedup();
+
+        let mut pools = self.pools.write().unwrap_or_else(|e| e.into_inner());
+        let previous = pools.get(service).cloned();
+        if let Some(previous) = &previous {
+            if previous.urls() == urls {
+                return false;
+            }
+        }
+        if urls.is_empty() {
+            warn!(service = %service, "Service has no endpoints left");
+        } else {
+            info!(service = %service, endpoints = ?urls, "Service endpoints changed");
+        }
+        pools.insert(service.to_string(), Arc::new(EndpointPool::new(urls, previous.as_deref())));
+        true
+    }
+
+    async fn lookup(&self, registration: &DiscoveredService) -> Result<Vec<String>, String> {
+        match self.config.backend {
+            DiscoveryBackend::Static => Err("static endpoints aren't looked up".to_string()),
+            DiscoveryBackend::Dns => {
+                let port = registration.port.unwrap_or(80);
+                let addresses = tokio::net::lookup_host((registration.name.as_str(), port))
+                    .await
+                    .map_err(|e| e.to_string())?;
+                Ok(addresses
+                    .map(|address| format!("{}://{}", registration.scheme, address))
+                    .collect())
+            }
+            DiscoveryBackend::Consul => {
+                let url = format!(
+                    "{}/v1/health/service/{}?passing=true",
+                    self.config.consul_url.trim_end_matches('/'),
+                    registration.name
+                );
+                let entries: Value = self.get_json(self.http_client.get(&url)).await?;
+                Ok(consul_endpoints(&entries, registration))
+            }
+            DiscoveryBackend::Kubernetes => {
+                let host = std::env::var("KUBERNETES_SERVICE_HOST")
+                    .map_err(|_| "KUBERNETES_SERVICE_HOST is not set".to_string())?;
+                let port = std::env::var("KUBERNETES_SERVICE_PORT").unwrap_or_else(|_| "443".to_string());
+                // Service account tokens are rotated, so it's read every time
+                let token = std::fs::read_to_string(KUBERNETES_TOKEN_PATH).map_err(|e| e.to_string())?;
+                let url = format!(
+                    "https://{}/api/v1/namespaces/{}/endpoints/{}",
+                    host_port(&host, &port),
+                    self.config.kubernetes_namespace,
+                    registration.name
+                );
+                let endpoints = self
+                    .get_json(self.http_client.get(&url).bearer_auth(token.trim()))
+                    .await?;
+                Ok(kubernetes_endpoints(&endpoints, registration))
+            }
+        }
+    }
+
+    async fn get_json(&self, request: reqwest::RequestBuilder) -> Result<Value, String> {
+        request
+            .send()
+            .await
+            .and_then(reqwest::Response::error_for_status)
+            .map_err(|e| e.to_string())?
+            .json()
+            .await
+            .map_err(|e| e.to_string())
+    }
+
+    /// Checks every endpoint of every service at once
+    pub async fn check_health(&self) {
+        let endpoints: Vec<(String, Arc<Endpoint>)> = self
+            .pools
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .flat_map(|(service, pool)| {
+                pool.endpoints
+                    .iter()
+                    .map(move |endpoint| (service.clone(), endpoint.clone()))
+            })
+            .collect();
+
+        let timeout = Duration::from_millis(self.config.health_check_timeout_ms);
+        let checks = endpoints.iter().map(|(service, endpoint)| async move {
+            let url = format!("{}{}", endpoint.url, self.config.health_check_path);
+            let passed = matches!(
+                self.http_client.get(&url).timeout(timeout).send().await,
+                Ok(response) if response.status().is_success()
+            );
+            self.record_check(service, endpoint, passed);
+        });
+        futures::future::join_all(checks).await;
+    }
+
+    fn record_check(&self, service: &str, endpoint: &Endpoint, passed: bool) {
+        let threshold = self.config.unhealthy_threshold;
+        if passed {
+            let failures = endpoint.consecutive_failures.swap(0, Ordering::Relaxed);
+            if failures >= threshold {
+                info!(service = %service, endpoint = %endpoint.url, "Endpoint passing health checks again");
+            }
+        } else {
+            let failures = endpoint.consecutive_failures.fetch_add(1, Ordering::Relaxed) + 1;
+            if failures == threshold {
+                warn!(service = %service, endpoint = %endpoint.url, failures, "Endpoint failing health checks, taken out of rotation");
+            }
+        }
+    }
+
+    /// Keep the pools in step with the backend and the endpoints' health
+    pub fn spawn_refresh(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut refresh = tokio::time::interval(Duration::from_secs(self.config.refresh_seconds.max(1)));
+            let mut health = (self.config.health_check_seconds > 0)
+                .then(|| tokio::time::interval(Duration::from_secs(self.config.health_check_seconds)));
+            loop {
+                tokio::select! {
+                    _ = refresh.tick() => {
+                        let refreshed = self.refresh().await;
+                        debug!(changed = ?refreshed.changed, failed = ?refreshed.failed, "Service endpoints refreshed");
+                    }
+                    _ = tick(&mut health) => self.check_health().await,
+                }
+            }
+        })
+    }
+
+    pub fn snapshot(&self) -> Vec<ServicePool> {
+        let threshold = self.config.unhealthy_threshold;
+        let mut pools: Vec<ServicePool> = self
+            .pools
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .iter()
+            .map(|(service, pool)| ServicePool {
+                service: service.clone(),
+                endpoints: pool
+                    .endpoints
+                    .iter()
+                    .map(|endpoint| {
+                        let consecutive_failures = endpoint.consecutive_failures.load(Ordering::Relaxed);
+                        EndpointStatus {
+                            url: endpoint.url.clone(),
+                            healthy: consecutive_failures < threshold,
+                            consecutive_failures,
+                        }
+                    })
+                    .collect(),
+            })
+            .collect();
+        pools.sort_by(|a, b| a.service.cmp(&b.service));
+        pools
+    }
+}
+
+/// Waits for the next tick, or forever when there's no interval
+async fn tick(interval: &mut Option<Interval>) {
+    match interval {
+        Some(interval) => {
+            interval.tick().await;
+        }
+        None => std::future::pending().await,
+    }
+}
+
+fn normalize_url(url: &str) -> String {
+    url.trim_end_matches('/').to_string()
+}
+
+/// `host:port`, with IPv6 addresses in brackets
+fn host_port(host: &str, port: &str) -> String {
+    if host.contains(':') {
+        format!("[{}]:{}", host, port)
+    } else {
+        format!("{}:{}", host, port)
+    }
+}
+
+/// Instances in a `/v1/health/service/:name` answer. The service address
+/// falls back to the node's when the service didn't register one.
+fn consul_endpoints(entries: &Value, registration: &DiscoveredService) -> Vec<String> {
+    entries
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|entry| {
+            let address = entry
+                .pointer("/Service/Address")
+                .and_then(Value::as_str)
+                .filter(|address| !address.is_empty())
+                .or_else(|| entry.pointer("/Node/Address").and_then(Value::as_str))?;
+            let port = registration
+                .port
+                .map(u64::from)
+                .or_else(|| entry.pointer("/Service/Port").and_then(Value::as_u64))?;
+            Some(format!("{}://{}", registration.scheme, host_port(address, &port.to_string())))
+        })
+        .collect()
+}
+
+/// Ready addresses in a Kubernetes Endpoints object. Without a configured
+/// port, the subset's first port is used.
+fn kubernetes_endpoints(endpoints: &Value, registration: &DiscoveredService) -> Vec<String> {
+    endpoints
+        .get("subsets")
+        .and_then(Value::as_array)
+        .into_iter()
+        .flatten()
+        .flat_map(|subset| {
+            let port = registration.port.map(u64::from).or_else(|| {
+                subset
+                    .pointer("/ports/0/port")
+                    .and_then(Value::as_u64)
+            });
+            subset
+                .get("addresses")
+                .and_then(Value::as_array)
+                .into_iter()
+                .flatten()
+                .filter_map(move |address| {
+                    let ip = address.get("ip").and_then(Value::as_str)?;
+                    Some(format!("{}://{}", registration.scheme, host_port(ip, &port?.to_string())))
+                })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ApiGatewayConfig;
+    use serde_json::json;
+
+    fn discovery() -> ServiceDiscovery {
+        let config = ApiGatewayConfig::development();
+        ServiceDiscovery::new(&config.discovery, &config.services).unwrap()
+    }
+
+    fn registration(port: Option<u16>) -> DiscoveredService {
+        DiscoveredService {
+            name: "user-service".to_string(),
+            port,
+            scheme: "http".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_static_pools_start_from_configured_urls() {
+        let discovery = discovery();
+
+        assert_eq!(discovery.resolve("user").unwrap().as_deref(), Some("http://localhost:8082"));
+        assert_eq!(discovery.resolve("white_label").unwrap().as_deref(), Some("http://localhost:8089"));
+        assert_eq!(discovery.resolve("billing").unwrap(), None);
+    }
+
+    #[test]
+    fn test_round_robin_skips_unhealthy_endpoints() {
+        let discovery = discovery();
+        discovery.replace_pool(
+            "user",
+            vec!["http://user-2:8082/".to_string(), "http://user-1:8082".to_string()],
+        );
+
+        let first = discovery.resolve("user").unwrap().unwrap();
+        let second = discovery.resolve("user").unwrap().unwrap();
+        assert_ne!(first, second);
+
+        let pool = discovery.pool("user").unwrap();
+        for _ in 0..3 {
+            discovery.record_check("user", &pool.endpoints[0], false);
+        }
+        for _ in 0..4 {
+            assert_eq!(discovery.resolve("user").unwrap().as_deref(), Some("http://user-2:8082"));
+        }
+
+        // With nothing healthy, every endpoint gets requests again
+        for _ in 0..3 {
+            discovery.record_check("user", &pool.endpoints[1], false);
+        }
+        let mut seen: Vec<String> = (0..4).map(|_| discovery.resolve("user").unwrap().unwrap()).collect();
+        seen.sort();
+        seen.dedup();
+        assert_eq!(seen.len(), 2);
+    }
+
+    #[test]
+    fn test_replacing_a_pool_keeps_known_endpoints_health() {
+        let discovery = discovery();
+        discovery.replace_pool("user", vec!["http://user-1:8082".to_string(), "http://user-2:8082".to_string()]);
+        let pool = discovery.pool("user").unwrap();
+        for _ in 0..3 {
+            discovery.record_check("user", &pool.endpoints[0], false);
+        }
+
+        assert!(!discovery.replace_pool("user", vec!["http://user-2:8082".to_string(), "http://user-1:8082".to_string()]));
+        assert!(discovery.replace_pool("user", vec!["http://user-1:8082".to_string(), "http://user-3:8082".to_string()]));
+
+        let status = discovery.snapshot().into_iter().find(|pool| pool.service == "user").unwrap();
+        assert_eq!(status.endpoints[0].url, "http://user-1:8082");
+        assert!(!status.endpoints[0].healthy);
+        assert!(status.endpoints[1].healthy);
+    }
+
+    #[test]
+    fn test_empty_pool_is_unavailable() {
+        let discovery = discovery();
+        discovery.replace_pool("user", Vec::new());
+
+        assert!(matches!(
+            discovery.resolve("user"),
+            Err(ApiGatewayError::ServiceUnavailable { .. })
+        ));
+    }
+
+    #[test]
+    fn test_endpoints_file_is_reloaded_when_changed() {
+        let path = std::env::temp_dir().join(format!("adx-endpoints-{}.json", uuid::Uuid::new_v4()));
+        std::fs::write(&path, r#"{"user": ["http://user-1:8082", "http://user-2:8082"]}"#).unwrap();
+
+        let mut config = ApiGatewayConfig::development();
+        config.discovery.endpoints_file = Some(path.to_string_lossy().to_string());
+        let discovery = ServiceDiscovery::new(&config.discovery, &config.services).unwrap();
+        assert_eq!(discovery.pool("user").unwrap().urls(), vec!["http://user-1:8082", "http://user-2:8082"]);
+
+        // Unchanged files aren't read again
+        assert!(discovery.reload_endpoints_file().unwrap().is_empty());
+
+        std::fs::write(&path, r#"{"tenant": ["http://tenant-1:8085"]}"#).unwrap();
+        *discovery.endpoints_file_modified.lock().unwrap() = None;
+        let changed = discovery.reload_endpoints_file().unwrap();
+        assert_eq!(changed, vec!["tenant", "user"]);
+        assert_eq!(discovery.pool("user").unwrap().urls(), vec!["http://localhost:8082"]);
+
+        std::fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_dns_discovery_needs_ports() {
+        let mut config = ApiGatewayConfig::development();
+        config.discovery.backend = DiscoveryBackend::Dns;
+        config.discovery.services.insert("user".to_string(), registration(None));
+
+        assert!(ServiceDiscovery::new(&config.discovery, &config.services).is_err());
+    }
+
+    #[test]
+    fn test_consul_endpoints() {
+        let entries = json!([
+            { "Node": { "Address": "10.0.0.1" }, "Service": { "Address": "10.1.0.1", "Port": 8082 } },
+            { "Node": { "Address": "10.0.0.2" }, "Service": { "Address": "", "Port": 9082 } },
+            { "Node": { "Address": "fd00::3" }, "Service": { "Port": 8082 } }
+        ]);
+
+        assert_eq!(
+            consul_endpoints(&entries, &registration(None)),
+            vec!["http://10.1.0.1:8082", "http://10.0.0.2:9082", "http://[fd00::3]:8082"]
+        );
+        assert_eq!(
+            consul_endpoints(&entries, &registration(Some(80)))[0],
+            "http://10.1.0.1:80"
+        );
+    }
+
+    #[test]
+    fn test_kubernetes_endpoints() {
+        let endpoints = json!({
+            "subsets": [{
+                "addresses": [{ "ip": "10.2.0.4" }, { "ip": "10.2.0.5" }],
+                "notReadyAddresses": [{ "ip": "10.2.0.6" }],
+                "ports": [{ "name": "http", "port": 8082 }]
+            }]
+        });
+
+        assert_eq!(
+            kubernetes_endpoints(&endpoints, &registration(None)),
+            vec!["http://10.2.0.4:8082", "http://10.2.0.5:8082"]
+        );
+        assert!(kubernetes_endpoints(&json!({}), &registration(None)).is_empty());
+    }
+}
@@ -8,6 +8,8 @@ use std::collections::HashMap;
 use thiserror::Error;
 use uuid::Uuid;
 
+use adx_shared::network_policy::NetworkDenialReason;
+
 #[derive(Error, Debug)]
 pub enum ApiGatewayError {
     #[error("Authentication required")]
@@ -28,12 +30,51 @@ pub enum ApiGatewayError {
     #[error("Tenant access denied: {reason}")]
     TenantAccessDenied { reason: String },
 
+    #[error("Tenant is read-only: {tenant_id}")]
+    TenantReadOnly { tenant_id: String, grace_ends_at: Option<chrono::DateTime<chrono::Utc>> },
+
+    #[error("Tenant is suspended: {tenant_id}")]
+    TenantSuspended { tenant_id: String },
+
+    #[error("Network access denied: {}", .reason.message())]
+    NetworkAccessDenied { reason: NetworkDenialReason },
+
     #[error("Service unavailable: {service}")]
     ServiceUnavailable { service: String },
 
     #[error("Service timeout: {service}")]
     ServiceTimeout { service: String },
 
+    #[error("Circuit open for service: {service}")]
+    CircuitOpen { service: String, retry_after: u64 },
+
+    #[error("Service at capacity: {service}")]
+    UpstreamSaturated { service: String },
+
+    #[error("Idempotency key was used for a different request: {key}")]
+    IdempotencyKeyReused { key: String },
+
+    #[error("A request with this idempotency key is still in progress: {key}")]
+    IdempotencyKeyInProgress { key: String },
+
+    #[error("No webhook integration named {integration}")]
+    WebhookIntegrationNotFound { integration: String },
+
+    #[error("Webhook delivery refused for {integration}: {reason}")]
+    WebhookRejected { integration: String, reason: String },
+
+    #[error("Webhook delivery {delivery_id} for {integration} is still being handled")]
+    WebhookInProgress { integration: String, delivery_id: String },
+
+    #[error("{message}")]
+    UnderMaintenance { message: String, tenant_id: Option<String>, retry_after: Option<u64> },
+
+    #[error("{message}")]
+    MaintenancePending { message: String, starts_at: chrono::DateTime<chrono::Utc> },
+
+    #[error("{message}")]
+    RouteDisabled { name: String, message: String },
+
     #[error("Workflow execution failed: {workflow_id}")]
     WorkflowExecutionFailed { workflow_id: String, error: String },
 
@@ -46,6 +87,9 @@ pub enum ApiGatewayError {
     #[error("Validation failed")]
     ValidationFailed { errors: Vec<ValidationError> },
 
+    #[error("Request does not match the API schema")]
+    SchemaViolation { errors: Vec<ValidationError> },
+
     #[error("Internal server error: {message}")]
     InternalError { message: String },
 
@@ -93,12 +137,26 @@ impl ApiGatewayError {
             ApiGatewayError::RateLimitExceeded { .. } => StatusCode::TOO_MANY_REQUESTS,
             ApiGatewayError::TenantNotFound { .. } => StatusCode::NOT_FOUND,
             ApiGatewayError::TenantAccessDenied { .. } => StatusCode::FORBIDDEN,
+            ApiGatewayError::TenantReadOnly { .. } => StatusCode::FORBIDDEN,
+            ApiGatewayError::TenantSuspended { .. } => StatusCode::PAYMENT_REQUIRED,
+            ApiGatewayError::NetworkAccessDenied { .. } => StatusCode::FORBIDDEN,
             ApiGatewayError::ServiceUnavailable { .. } => StatusCode::SERVICE_UNAVAILABLE,
             ApiGatewayError::ServiceTimeout { .. } => StatusCode::GATEWAY_TIMEOUT,
+            ApiGatewayError::CircuitOpen { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            ApiGatewayError::UpstreamSaturated { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            ApiGatewayError::UnderMaintenance { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            ApiGatewayError::MaintenancePending { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            ApiGatewayError::RouteDisabled { .. } => StatusCode::SERVICE_UNAVAILABLE,
+            ApiGatewayError::IdempotencyKeyReused { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiGatewayError::IdempotencyKeyInProgress { .. } => StatusCode::CONFLICT,
+            ApiGatewayError::WebhookIntegrationNotFound { .. } => StatusCode::NOT_FOUND,
+            ApiGatewayError::WebhookRejected { .. } => StatusCode::UNAUTHORIZED,
+            ApiGatewayError::WebhookInProgress { .. } => StatusCode::CONFLICT,
             ApiGatewayError::WorkflowNotFound { .. } => StatusCode::NOT_FOUND,
             ApiGatewayError::WorkflowExecutionFailed { .. } => StatusCode::INTERNAL_SERVER_ERROR,
             ApiGatewayError::InvalidRequest { .. } => StatusCode::BAD_REQUEST,
             ApiGatewayError::ValidationFailed { .. } => StatusCode::UNPROCESSABLE_ENTITY,
+            ApiGatewayError::SchemaViolation { .. } => StatusCode::BAD_REQUEST,
             ApiGatewayError::InternalError { .. } => StatusCode::INTERNAL_SERVER_ERROR,
             ApiGatewayError::TemporalError { .. } => StatusCode::INTERNAL_SERVER_ERROR,
             ApiGatewayError::RedisError { .. } => StatusCode::INTERNAL_SERVER_ERROR,
@@ -114,12 +172,26 @@ impl ApiGatewayError {
             ApiGatewayError::RateLimitExceeded { .. } => "RATE_LIMIT_EXCEEDED",
             ApiGatewayError::TenantNotFound { .. } => "TENANT_NOT_FOUND",
             ApiGatewayError::TenantAccessDenied { .. } => "TENANT_ACCESS_DENIED",
+            ApiGatewayError::TenantReadOnly { .. } => "TENANT_READ_ONLY",
+            ApiGatewayError::TenantSuspended { .. } => "TENANT_SUSPENDED",
+            ApiGatewayError::NetworkAccessDenied { .. } => "NETWORK_ACCESS_DENIED",
             ApiGatewayError::ServiceUnavailable { .. } => "SERVICE_UNAVAILABLE",
             ApiGatewayError::ServiceTimeout { .. } => "SERVICE_TIMEOUT",
+            ApiGatewayError::CircuitOpen { .. } => "CIRCUIT_OPEN",
+            ApiGatewayError::UpstreamSaturated { .. } => "UPSTREAM_SATURATED",
+            ApiGatewayError::UnderMaintenance { .. } => "MAINTENANCE",
+            ApiGatewayError::MaintenancePending { .. } => "MAINTENANCE_PENDING",
+            ApiGatewayError::RouteDisabled { .. } => "ROUTE_DISABLED",
+            ApiGatewayError::IdempotencyKeyReused { .. } => "IDEMPOTENCY_KEY_REUSED",
+            ApiGatewayError::IdempotencyKeyInProgress { .. } => "IDEMPOTENCY_KEY_IN_PROGRESS",
+            ApiGatewayError::WebhookIntegrationNotFound { .. } => "WEBHOOK_INTEGRATION_NOT_FOUND",
+            ApiGatewayError::WebhookRejected { .. } => "WEBHOOK_REJECTED",
+            ApiGatewayError::WebhookInProgress { .. } => "WEBHOOK_IN_PROGRESS",
             ApiGatewayError::WorkflowNotFound { .. } => "WORKFLOW_NOT_FOUND",
             ApiGatewayError::WorkflowExecutionFailed { .. } => "WORKFLOW_EXECUTION_FAILED",
             ApiGatewayError::InvalidRequest { .. } => "INVALID_REQUEST",
             ApiGatewayError::ValidationFailed { .. } => "VALIDATION_FAILED",
+            ApiGatewayError::SchemaViolation { .. } => "SCHEMA_VIOLATION",
             ApiGatewayError::InternalError { .. } => "INTERNAL_ERROR",
             ApiGatewayError::TemporalError { .. } => "TEMPORAL_ERROR",
             ApiGatewayError::RedisError { .. } => "REDIS_ERROR",
@@ -147,7 +219,29 @@ impl ApiGatewayError {
                     "limit_type": limit_type
                 }));
             }
-            ApiGatewayError::ValidationFailed { errors } => {
+            ApiGatewayError::CircuitOpen { service, retry_after } => {
+                details.retry_after = Some(*retry_after);
+                details.details = Some(serde_json::json!({
+                    "service": service
+                }));
+            }
+            ApiGatewayError::UnderMaintenance { tenant_id, retry_after, .. } => {
+                details.retry_after = *retry_after;
+                details.details = Some(serde_json::json!({
+                    "tenant_id": tenant_id
+                }));
+            }
+            ApiGatewayError::MaintenancePending { starts_at, .. } => {
+                details.details = Some(serde_json::json!({
+                    "starts_at": starts_at
+                }));
+            }
+            ApiGatewayError::RouteDisabled { name, .. } => {
+                details.details = Some(serde_json::json!({
+                    "kill_switch": name
+                }));
+            }
+            ApiGatewayError::ValidationFailed { errors } | ApiGatewayError::SchemaViolation { errors } => {
                 details.validation_errors = Some(errors.clone());
             }
             ApiGatewayError::InsufficientPermissions { required_permission } => {
@@ -155,6 +249,16 @@ impl ApiGatewayError {
                     "required_permission": required_permission
                 }));
             }
+            ApiGatewayError::TenantReadOnly { grace_ends_at, .. } => {
+                details.details = Some(serde_json::json!({
+                    "grace_ends_at": grace_ends_at
+                }));
+            }
+            ApiGatewayError::NetworkAccessDenied { reason } => {
+                details.details = Some(serde_json::json!({
+                    "reason": reason
+                }));
+            }
             ApiGatewayError::WorkflowExecutionFailed { workflow_id, error } => {
                 details.details = Some(serde_json::json!({
                     "workflow_id": workflow_id,
@@ -180,8 +284,14 @@ impl IntoResponse for ApiGatewayError {
         let mut response = Json(error_response).into_response();
         *response.status_mut() = status;
         
-        // Add retry-after header for rate limiting
-        if let ApiGatewayError::RateLimitExceeded { retry_after, .. } = self {
+        // Add retry-after header for rate limiting and open circuits
+        if let ApiGatewayError::RateLimitExceeded { retry_after, .. } | ApiGatewayError::CircuitOpen { retry_after, .. } = self {
+            response.headers_mut().insert(
+                "Retry-After",
+                retry_after.to_string().parse().unwrap(),
+            );
+        }
+        if let ApiGatewayError::UnderMaintenance { retry_after: Some(retry_after), .. } = self {
             response.headers_mut().insert(
                 "Retry-After",
                 retry_after.to_string().parse().unwrap(),
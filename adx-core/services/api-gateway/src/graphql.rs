@@ -0,0 +1,579 @@
+//! Read-only GraphQL endpoint over the core services.
+//!
+//! Composite screens can fetch users, tenants, files and workflow status in
+//! one request instead of chaining REST calls. Resolvers call the services'
+//! REST endpoints with the caller's credentials, through per-request
+//! dataloaders: every id asked for in one execution step is fetched once,
+//! concurrently, however many fields refer to it.
+
+use async_graphql::dataloader::{DataLoader, Loader};
+use async_graphql::{
+    ComplexObject, Context, EmptyMutation, EmptySubscription, Enum, Object, Schema, SimpleObject, ID,
+};
+use axum::http::HeaderMap;
+use chrono::{DateTime, Utc};
+use futures::future::join_all;
+use reqwest::StatusCode;
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::marker::PhantomData;
+use std::sync::Arc;
+use tracing::debug;
+
+use adx_shared::context_token::TENANT_CONTEXT_HEADER;
+use adx_shared::telemetry::TracedRequestExt;
+use crate::config::{ApiGatewayConfig, GraphqlConfig};
+use crate::error::{ApiGatewayError, ApiResult};
+use crate::middleware::RequestContext;
+use crate::routing::{DirectOperation, IntelligentRouter};
+use crate::temporal_client::{
+    ApiGatewayTemporalClient, WorkflowProgress, WorkflowStatus, WorkflowStatusResponse,
+};
+use crate::upstreams::UpstreamRegistry;
+
+pub type GatewaySchema = Schema<QueryRoot, EmptyMutation, EmptySubscription>;
+
+type LoaderError = Arc<ApiGatewayError>;
+
+pub fn build_schema(config: &GraphqlConfig) -> GatewaySchema {
+    Schema::build(QueryRoot, EmptyMutation, EmptySubscription)
+        .limit_depth(config.max_depth)
+        .limit_complexity(config.max_complexity)
+        .finish()
+}
+
+/// Per-request data a query executes with
+pub fn request_data(
+    request: async_graphql::Request,
+    backend: Backend,
+    temporal_client: Arc<ApiGatewayTemporalClient>,
+    context: RequestContext,
+) -> async_graphql::Request {
+    let backend = Arc::new(backend);
+    request
+        .data(DataLoader::new(
+            BackendLoader::<UserNode>::new(backend.clone(), |id| format!("/api/v1/users/{}", id)),
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(
+            BackendLoader::<TenantNode>::new(backend.clone(), |id| format!("/api/v1/tenants/{}", id)),
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(
+            BackendLoader::<FileNode>::new(backend.clone(), |id| format!("/api/v1/files/{}", id)),
+            tokio::spawn,
+        ))
+        .data(DataLoader::new(WorkflowLoader { temporal_client }, tokio::spawn))
+        .data(backend)
+        .data(context)
+}
+
+/// The services' REST endpoints, called as the requesting user
+pub struct Backend {
+    http_client: reqwest::Client,
+    router: Arc<IntelligentRouter>,
+    upstreams: Arc<UpstreamRegistry>,
+    config: Arc<ApiGatewayConfig>,
+    headers: Vec<(String, String)>,
+}
+
+impl Backend {
+    /// Calls carry the caller's token, tenant and request id
+    pub fn new(
+        http_client: reqwest::Client,
+        router: Arc<IntelligentRouter>,
+        upstreams: Arc<UpstreamRegistry>,
+        config: Arc<ApiGatewayConfig>,
+        request_headers: &HeaderMap,
+        context: &RequestContext,
+    ) -> Self {
+        let mut headers: Vec<(String, String)> = ["Authorization", TENANT_CONTEXT_HEADER]
+            .iter()
+            .filter_map(|name| {
+                request_headers
+                    .get(*name)
+                    .and_then(|value| value.to_str().ok())
+                    .map(|value| (name.to_string(), value.to_string()))
+            })
+            .collect();
+        headers.push(("X-Request-ID".to_string(), context.request_id.clone()));
+        if let Some(tenant_context) = &context.tenant_context {
+            headers.push(("X-Tenant-ID".to_string(), tenant_context.tenant_id.clone()));
+        }
+
+        Self {
+            http_client,
+            router,
+            upstreams,
+            config,
+            headers,
+        }
+    }
+
+    /// GET a resource; None when the service doesn't have it
+    async fn get<T: DeserializeOwned>(&self, path_and_query: &str) -> ApiResult<Option<T>> {
+        let path = path_and_query.split('?').next().unwrap_or(path_and_query);
+        let route = self.router.get_service_route(&DirectOperation::Read, path)?;
+        let service = route.service_name.clone();
+
+        let mut request = self
+            .http_client
+            .get(self.router.build_service_url(&route, path_and_query))
+            .timeout(self.config.service_timeout(&service))
+            .with_trace_context();
+        for (name, value) in &self.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+
+        let permit = self.upstreams.acquire(&service).await?;
+        let response = match request.send().await {
+            Ok(response) => response,
+            Err(e) => {
+                permit.failure();
+                return Err(if e.is_timeout() {
+                    ApiGatewayError::ServiceTimeout { service: service.clone() }
+                } else {
+                    ApiGatewayError::ServiceUnavailable { service: service.clone() }
+                });
+            }
+        };
+        permit.record_status(response.status().as_u16());
+
+        match response.status() {
+            StatusCode::NOT_FOUND => return Ok(None),
+            StatusCode::UNAUTHORIZED => return Err(ApiGatewayError::AuthenticationRequired),
+            StatusCode::FORBIDDEN => {
+                return Err(ApiGatewayError::TenantAccessDenied {
+                    reason: format!("Refused by the {} service", service),
+                })
+            }
+            status if !status.is_success() => {
+                debug!(service = %service, status = %status, path = %path, "GraphQL backend call failed");
+                return Err(ApiGatewayError::ServiceUnavailable { service });
+            }
+            _ => {}
+        }
+
+        let body: Value = response.json().await.map_err(|e| ApiGatewayError::InternalError {
+            message: format!("Invalid response from the {} service: {}", service, e),
+        })?;
+        let Some(data) = unwrap_envelope(body) else {
+            return Ok(None);
+        };
+
+        serde_json::from_value(data)
+            .map(Some)
+            .map_err(|e| ApiGatewayError::InternalError {
+                message: format!("Unexpected response from the {} service: {}", service, e),
+            })
+    }
+}
+
+/// Some services wrap their data in `{ success, data, error }`; a failed or
+/// empty envelope has nothing to give
+fn unwrap_envelope(body: Value) -> Option<Value> {
+    if body.get("success").and_then(Value::as_bool).is_some() {
+        return body.get("data").filter(|data| !data.is_null()).cloned();
+    }
+    Some(body)
+}
+
+/// Ids go into backend paths, so anything that could escape the segment is
+/// treated as unknown
+fn is_path_segment(id: &str) -> bool {
+    !id.is_empty()
+        && id.len() <= 128
+        && id.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+/// Loads one kind of resource by id from its service
+pub struct BackendLoader<T> {
+    backend: Arc<Backend>,
+    path: fn(&str) -> String,
+    node: PhantomData<fn() -> T>,
+}
+
+impl<T> BackendLoader<T> {
+    fn new(backend: Arc<Backend>, path: fn(&str) -> String) -> Self {
+        Self {
+            backend,
+            path,
+            node: PhantomData,
+        }
+    }
+}
+
+impl<T> Loader<String> for BackendLoader<T>
+where
+    T: DeserializeOwned + Clone + Send + Sync + 'static,
+{
+    type Value = T;
+    type Error = LoaderError;
+
+    async fn load(&self, keys: &[String]) -> Result<HashMap<String, T>, LoaderError> {
+        let lookups = keys.iter().filter(|key| is_path_segment(key)).map(|key| async move {
+            let node = self.backend.get::<T>(&(self.path)(key)).await;
+            (key.clone(), node)
+        });
+
+        let mut found = HashMap::new();
+        for (key, node) in join_all(lookups).await {
+            if let Some(node) = node.map_err(Arc::new)? {
+                found.insert(key, node);
+            }
+        }
+        Ok(found)
+    }
+}
+
+/// Loads workflow status from Temporal
+pub struct WorkflowLoader {
+    temporal_client: Arc<ApiGatewayTemporalClient>,
+}
+
+impl Loader<String> for WorkflowLoader {
+    type Value = WorkflowNode;
+    type Error = LoaderError;
+
+    async fn load(&self, keys: &[String]) -> Result<HashMap<String, WorkflowNode>, LoaderError> {
+        let lookups = keys.iter().map(|key| async move {
+            (key.clone(), self.temporal_client.get_workflow_status(key).await)
+        });
+
+        let mut found = HashMap::new();
+        for (key, status) in join_all(lookups).await {
+            match status {
+                Ok(status) => {
+                    found.insert(key, WorkflowNode::from(status));
+                }
+                Err(ApiGatewayError::WorkflowNotFound { .. }) => {}
+                Err(e) => return Err(Arc::new(e)),
+            }
+        }
+        Ok(found)
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, SimpleObject)]
+#[graphql(name = "User", complex)]
+pub struct UserNode {
+    pub id: ID,
+    pub tenant_id: ID,
+    pub email: String,
+    pub first_name: Option<String>,
+    pub last_name: Option<String>,
+    pub status: String,
+    #[serde(default)]
+    pub roles: Vec<String>,
+    pub last_login_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[ComplexObject]
+impl UserNode {
+    async fn tenant(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<TenantNode>> {
+        load_tenant(ctx, &self.tenant_id).await
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, SimpleObject)]
+#[graphql(name = "Tenant", complex)]
+pub struct TenantNode {
+    pub id: ID,
+    pub name: String,
+    pub slug: String,
+    pub admin_email: String,
+    pub subscription_tier: String,
+    pub status: String,
+    #[serde(default)]
+    pub features: Vec<String>,
+    #[serde(default)]
+    pub parent_tenant_id: Option<ID>,
+    #[serde(default)]
+    pub region: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[ComplexObject]
+impl TenantNode {
+    /// The parent of a sub-tenant
+    async fn parent(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<TenantNode>> {
+        match &self.parent_tenant_id {
+            Some(parent_tenant_id) => load_tenant(ctx, parent_tenant_id).await,
+            None => Ok(None),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Deserialize, SimpleObject)]
+#[graphql(name = "File", complex)]
+pub struct FileNode {
+    pub id: ID,
+    pub tenant_id: ID,
+    pub user_id: ID,
+    pub filename: String,
+    pub original_filename: String,
+    pub mime_type: String,
+    pub file_size: i64,
+    pub status: String,
+    pub is_public: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[ComplexObject]
+impl FileNode {
+    /// The user who uploaded the file
+    async fn owner(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<UserNode>> {
+        load_user(ctx, &self.user_id).await
+    }
+
+    async fn tenant(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<TenantNode>> {
+        load_tenant(ctx, &self.tenant_id).await
+    }
+}
+
+/// A page of the tenant's files
+#[derive(Debug, Clone, Deserialize, SimpleObject)]
+#[graphql(name = "FilePage")]
+pub struct FilePage {
+    pub files: Vec<FileNode>,
+    pub total: i64,
+    pub page: i32,
+    pub per_page: i32,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Enum)]
+#[graphql(name = "WorkflowState")]
+pub enum WorkflowState {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+    TimedOut,
+}
+
+impl From<WorkflowStatus> for WorkflowState {
+    fn from(status: WorkflowStatus) -> Self {
+        match status {
+            WorkflowStatus::Pending => Self::Pending,
+            WorkflowStatus::Running => Self::Running,
+            WorkflowStatus::Completed => Self::Completed,
+            WorkflowStatus::Failed => Self::Failed,
+            WorkflowStatus::Cancelled => Self::Cancelled,
+            WorkflowStatus::TimedOut => Self::TimedOut,
+        }
+    }
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+#[graphql(name = "WorkflowProgress")]
+pub struct WorkflowProgressNode {
+    pub current_step: String,
+    pub total_steps: u32,
+    pub completed_steps: u32,
+    pub percentage: f32,
+    pub message: Option<String>,
+}
+
+impl From<WorkflowProgress> for WorkflowProgressNode {
+    fn from(progress: WorkflowProgress) -> Self {
+        Self {
+            current_step: progress.current_step,
+            total_steps: progress.total_steps,
+            completed_steps: progress.completed_steps,
+            percentage: progress.percentage,
+            message: progress.message,
+        }
+    }
+}
+
+#[derive(Debug, Clone, SimpleObject)]
+#[graphql(name = "Workflow")]
+pub struct WorkflowNode {
+    pub operation_id: ID,
+    pub status: WorkflowState,
+    pub progress: Option<WorkflowProgressNode>,
+    pub result: Option<async_graphql::Json<Value>>,
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub estimated_completion: Option<DateTime<Utc>>,
+}
+
+impl From<WorkflowStatusResponse> for WorkflowNode {
+    fn from(status: WorkflowStatusResponse) -> Self {
+        Self {
+            operation_id: ID(status.operation_id),
+            status: status.status.into(),
+            progress: status.progress.map(WorkflowProgressNode::from),
+            result: status.result.map(async_graphql::Json),
+            error: status.error,
+            started_at: status.started_at,
+            updated_at: status.updated_at,
+            estimated_completion: status.estimated_completion,
+        }
+    }
+}
+
+pub struct QueryRoot;
+
+#[Object]
+impl QueryRoot {
+    /// The signed-in user
+    async fn me(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<UserNode>> {
+        let user_id = ctx
+            .data_opt::<RequestContext>()
+            .and_then(|context| context.user_context.as_ref())
+            .map(|user| user.user_id.clone());
+        match user_id {
+            Some(user_id) => load_user(ctx, &user_id).await,
+            None => Ok(None),
+        }
+    }
+
+    /// The tenant the request is made in
+    async fn current_tenant(&self, ctx: &Context<'_>) -> async_graphql::Result<Option<TenantNode>> {
+        let tenant_id = ctx
+            .data_opt::<RequestContext>()
+            .and_then(|context| context.tenant_context.as_ref())
+            .map(|tenant| tenant.tenant_id.clone());
+        match tenant_id {
+            Some(tenant_id) => load_tenant(ctx, &tenant_id).await,
+            None => Ok(None),
+        }
+    }
+
+    async fn user(&self, ctx: &Context<'_>, id: ID) -> async_graphql::Result<Option<UserNode>> {
+        load_user(ctx, &id).await
+    }
+
+    /// Users of the current tenant
+    async fn users(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 50, validator(minimum = 1, maximum = 100))] limit: i64,
+        #[graphql(default = 0, validator(minimum = 0))] offset: i64,
+    ) -> async_graphql::Result<Vec<UserNode>> {
+        let users = ctx
+            .data::<Arc<Backend>>()?
+            .get::<Vec<UserNode>>(&format!("/api/v1/users?limit={}&offset={}", limit, offset))
+            .await?;
+        Ok(users.unwrap_or_default())
+    }
+
+    async fn tenant(&self, ctx: &Context<'_>, id: ID) -> async_graphql::Result<Option<TenantNode>> {
+        load_tenant(ctx, &id).await
+    }
+
+    async fn file(&self, ctx: &Context<'_>, id: ID) -> async_graphql::Result<Option<FileNode>> {
+        Ok(ctx.data::<DataLoader<BackendLoader<FileNode>>>()?.load_one(id.to_string()).await?)
+    }
+
+    /// Files of the current tenant the user can see
+    async fn files(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(default = 1, validator(minimum = 1))] page: i32,
+        #[graphql(default = 20, validator(minimum = 1, maximum = 100))] per_page: i32,
+    ) -> async_graphql::Result<Option<FilePage>> {
+        Ok(ctx
+            .data::<Arc<Backend>>()?
+            .get::<FilePage>(&format!("/api/v1/files?page={}&per_page={}", page, per_page))
+            .await?)
+    }
+
+    /// Status of a workflow started through the gateway
+    async fn workflow(&self, ctx: &Context<'_>, operation_id: ID) -> async_graphql::Result<Option<WorkflowNode>> {
+        Ok(ctx.data::<DataLoader<WorkflowLoader>>()?.load_one(operation_id.to_string()).await?)
+    }
+
+    /// Status of several workflows, e.g. for an operations list
+    async fn workflows(
+        &self,
+        ctx: &Context<'_>,
+        #[graphql(validator(max_items = 100))] operation_ids: Vec<ID>,
+    ) -> async_graphql::Result<Vec<Option<WorkflowNode>>> {
+        let loader = ctx.data::<DataLoader<WorkflowLoader>>()?;
+        let keys: Vec<String> = operation_ids.iter().map(|id| id.to_string()).collect();
+        let mut found = loader.load_many(keys.iter().cloned()).await?;
+        Ok(keys.iter().map(|key| found.remove(key)).collect())
+    }
+}
+
+async fn load_user(ctx: &Context<'_>, id: &str) -> async_graphql::Result<Option<UserNode>> {
+    Ok(ctx.data::<DataLoader<BackendLoader<UserNode>>>()?.load_one(id.to_string()).await?)
+}
+
+async fn load_tenant(ctx: &Context<'_>, id: &str) -> async_graphql::Result<Option<TenantNode>> {
+    Ok(ctx.data::<DataLoader<BackendLoader<TenantNode>>>()?.load_one(id.to_string()).await?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_unwrap_envelope() {
+        assert_eq!(
+            unwrap_envelope(json!({ "success": true, "data": { "id": "1" }, "error": null })),
+            Some(json!({ "id": "1" }))
+        );
+        assert_eq!(unwrap_envelope(json!({ "success": false, "data": null, "error": "boom" })), None);
+        assert_eq!(unwrap_envelope(json!({ "id": "t1" })), Some(json!({ "id": "t1" })));
+    }
+
+    #[test]
+    fn test_path_segment_validation() {
+        assert!(is_path_segment("9b2f6c1e-0d6a-4f0e-9a57-4f0b2a1c3d4e"));
+        assert!(is_path_segment("tenant_1"));
+        assert!(!is_path_segment(""));
+        assert!(!is_path_segment("../admin"));
+        assert!(!is_path_segment("1?include=all"));
+    }
+
+    #[test]
+    fn test_user_from_service_response() {
+        let user: UserNode = serde_json::from_value(json!({
+            "id": "9b2f6c1e-0d6a-4f0e-9a57-4f0b2a1c3d4e",
+            "tenant_id": "tenant-1",
+            "email": "ada@example.com",
+            "first_name": "Ada",
+            "last_name": null,
+            "status": "Active",
+            "roles": ["admin"],
+            "permissions": [],
+            "preferences": {},
+            "last_login_at": null,
+            "email_verified_at": null,
+            "created_at": "2024-01-01T00:00:00Z",
+            "updated_at": "2024-01-01T00:00:00Z",
+            "profile": null
+        }))
+        .unwrap();
+
+        assert_eq!(user.tenant_id.as_str(), "tenant-1");
+        assert_eq!(user.roles, vec!["admin"]);
+    }
+
+    #[test]
+    fn test_schema_is_read_only() {
+        let sdl = build_schema(&GraphqlConfig {
+            enabled: true,
+            max_depth: 10,
+            max_complexity: 500,
+        })
+        .sdl();
+
+        assert!(sdl.contains("type Query"));
+        assert!(sdl.contains("currentTenant"));
+        assert!(sdl.contains("owner: User"));
+        assert!(!sdl.contains("type Mutation"));
+    }
+}
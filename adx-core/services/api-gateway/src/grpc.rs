@@ -0,0 +1,193 @@
+//! gRPC routing to the services' internal gRPC APIs.
+//!
+//! Requests with an `application/grpc` content type are passed through over
+//! HTTP/2 to the service serving the requested protobuf package, e.g.
+//! `/adx.quota.v1.QuotaService/CheckQuota` to license-service. Messages
+//! aren't decoded; the gateway only picks the service, applies its circuit
+//! breaker and continues the trace. Errors are answered the way a gRPC
+//! server would, as a `grpc-status` on an otherwise empty response.
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, HeaderMap, HeaderName, HeaderValue, StatusCode, Uri},
+    response::Response,
+};
+use hyper_util::{
+    client::legacy::{connect::HttpConnector, Client},
+    rt::TokioExecutor,
+};
+use std::time::Duration;
+use tracing::{debug, warn};
+
+use crate::config::{GrpcConfig, GrpcRoute};
+use crate::error::ApiGatewayError;
+use crate::upstreams::UpstreamRegistry;
+use adx_shared::telemetry::current_trace_headers;
+
+/// gRPC status codes the gateway answers with itself
+const UNIMPLEMENTED: u16 = 12;
+const UNAVAILABLE: u16 = 14;
+const DEADLINE_EXCEEDED: u16 = 4;
+
+pub struct GrpcRouter {
+    client: Client<HttpConnector, Body>,
+    /// Longest package prefix first, so the most specific route wins
+    routes: Vec<(String, GrpcRoute, Uri)>,
+    timeout: Duration,
+}
+
+impl GrpcRouter {
+    pub fn new(config: &GrpcConfig) -> Result<Self, ApiGatewayError> {
+        let mut routes = config
+            .routes
+            .iter()
+            .map(|(package, route)| {
+                let endpoint = route.endpoint.parse::<Uri>().map_err(|e| ApiGatewayError::ConfigurationError {
+                    message: format!("Invalid gRPC endpoint {} for {}: {}", route.endpoint, package, e),
+                })?;
+                Ok((package.clone(), route.clone(), endpoint))
+            })
+            .collect::<Result<Vec<_>, ApiGatewayError>>()?;
+        routes.sort_by(|a, b| b.0.len().cmp(&a.0.len()));
+
+        Ok(Self {
+            client: Client::builder(TokioExecutor::new()).http2_only(true).build_http(),
+            routes,
+            timeout: Duration::from_secs(config.timeout_seconds),
+        })
+    }
+
+    pub fn is_grpc(headers: &HeaderMap) -> bool {
+        headers
+            .get(header::CONTENT_TYPE)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.starts_with("application/grpc"))
+            .unwrap_or(false)
+    }
+
+    /// Route for a `/package.Service/Method` path
+    fn route_for(&self, path: &str) -> Option<&(String, GrpcRoute, Uri)> {
+        let service = path.trim_start_matches('/').split('/').next()?;
+        self.routes.iter().find(|(package, _, _)| {
+            service
+                .strip_prefix(package.as_str())
+                .map(|rest| rest.starts_with('.'))
+                .unwrap_or(false)
+        })
+    }
+
+    pub async fn forward(&self, upstreams: &UpstreamRegistry, request: Request) -> Response {
+        let path = request.uri().path().to_string();
+        let Some((_, route, endpoint)) = self.route_for(&path) else {
+            return grpc_error(UNIMPLEMENTED, &format!("No service serves {}", path));
+        };
+
+        let permit = match upstreams.acquire(&route.service).await {
+            Ok(permit) => permit,
+            Err(_) => return grpc_error(UNAVAILABLE, &format!("{} is unavailable", route.service)),
+        };
+
+        let mut parts = endpoint.clone().into_parts();
+        parts.path_and_query = request.uri().path_and_query().cloned();
+        let target = match Uri::from_parts(parts) {
+            Ok(target) => target,
+            Err(e) => return grpc_error(UNIMPLEMENTED, &format!("Invalid gRPC path {}: {}", path, e)),
+        };
+
+        let (mut head, body) = request.into_parts();
+        head.uri = target;
+        head.version = axum::http::Version::HTTP_2;
+        forward_headers(&mut head.headers);
+        let upstream_request = Request::from_parts(head, body);
+
+        debug!(path = %path, service = %route.service, "Forwarding gRPC request");
+        match tokio::time::timeout(self.timeout, self.client.request(upstream_request)).await {
+            Ok(Ok(response)) => {
+                permit.record_status(response.status().as_u16());
+                response.map(Body::new)
+            }
+            Ok(Err(e)) => {
+                permit.failure();
+                warn!(path = %path, service = %route.service, "gRPC request failed: {}", e);
+                grpc_error(UNAVAILABLE, &format!("{} is unavailable", route.service))
+            }
+            Err(_) => {
+                permit.failure();
+                grpc_error(DEADLINE_EXCEEDED, &format!("{} did not answer in time", route.service))
+            }
+        }
+    }
+}
+
+/// Drops connection-level headers and replaces the client's trace context
+/// with the gateway's span
+fn forward_headers(headers: &mut HeaderMap) {
+    for name in [
+        "connection",
+        "keep-alive",
+        "proxy-authorization",
+        "transfer-encoding",
+        "upgrade",
+        "host",
+        "traceparent",
+        "tracestate",
+    ] {
+        headers.remove(name);
+    }
+    for (name, value) in current_trace_headers() {
+        if let (Ok(name), Ok(value)) = (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(&value)) {
+            headers.insert(name, value);
+        }
+    }
+}
+
+/// Trailers-only gRPC response carrying an error status
+fn grpc_error(code: u16, message: &str) -> Response {
+    let mut response = Response::new(Body::empty());
+    *response.status_mut() = StatusCode::OK;
+    let headers = response.headers_mut();
+    headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/grpc"));
+    headers.insert("grpc-status", HeaderValue::from(code));
+    if let Ok(message) = HeaderValue::from_str(message) {
+        headers.insert("grpc-message", message);
+    }
+    response
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ApiGatewayConfig;
+
+    #[test]
+    fn test_routes_by_package() {
+        let grpc = GrpcRouter::new(&ApiGatewayConfig::development().grpc).unwrap();
+
+        let (_, route, _) = grpc.route_for("/adx.quota.v1.QuotaService/CheckQuota").unwrap();
+        assert_eq!(route.service, "license");
+        let (_, route, _) = grpc.route_for("/adx.tenant.v1.PermissionService/CheckPermission").unwrap();
+        assert_eq!(route.service, "tenant");
+
+        assert!(grpc.route_for("/adx.quotas.v1.QuotaService/CheckQuota").is_none());
+        assert!(grpc.route_for("/grpc.health.v1.Health/Check").is_none());
+    }
+
+    #[test]
+    fn test_detects_grpc_content_types() {
+        let mut headers = HeaderMap::new();
+        assert!(!GrpcRouter::is_grpc(&headers));
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/grpc+proto"));
+        assert!(GrpcRouter::is_grpc(&headers));
+        headers.insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        assert!(!GrpcRouter::is_grpc(&headers));
+    }
+
+    #[test]
+    fn test_errors_are_trailers_only() {
+        let response = grpc_error(UNIMPLEMENTED, "No service serves /x.Y/Z");
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()["grpc-status"], "12");
+        assert_eq!(response.headers()["grpc-message"], "No service serves /x.Y/Z");
+    }
+}
@@ -1,6 +1,7 @@
 use axum::{
+    body::Bytes,
     extract::{Path, Query, State, Request},
-    http::{Method, StatusCode},
+    http::{header, HeaderMap, Method, StatusCode},
     response::{IntoResponse, Response},
     Json,
 };
@@ -9,12 +10,31 @@ use serde_json::Value;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tracing::{debug, info, warn, error};
+use utoipa::{IntoParams, ToSchema};
 
 use crate::config::ApiGatewayConfig;
 use crate::error::{ApiGatewayError, ApiResult};
+use crate::graphql::{self, GatewaySchema};
+use crate::grpc::GrpcRouter;
+use crate::idempotency::{self, IDEMPOTENCY_KEY_HEADER};
+use crate::discovery::{DiscoveryRefresh, ServiceDiscovery, ServicePool};
+use crate::maintenance::{
+    EnableMaintenanceRequest, KillSwitch, KillSwitchRequest, MaintenanceControl, MaintenanceStatus,
+    MaintenanceWindow,
+};
 use crate::middleware::{MiddlewareState, RequestContext};
+use crate::mirror::{MirrorStatus, PrimaryResponse, ShadowRequest, TrafficMirror};
+use crate::openapi::OpenApiRegistry;
+use crate::rate_limiter::TenantRateLimitStatus;
 use crate::routing::{IntelligentRouter, OperationType, DirectOperation, WorkflowOperation};
-use crate::temporal_client::{ApiGatewayTemporalClient, WorkflowExecutionResponse};
+use crate::temporal_client::{ApiGatewayTemporalClient, WorkflowExecutionResponse, WorkflowStatus};
+use crate::upstreams::{UpstreamRegistry, UpstreamStatus};
+use crate::webhooks::{ArchivedDelivery, WebhookReceipt, WebhookReceiver};
+use crate::websocket;
+use adx_shared::telemetry::TracedRequestExt;
+
+/// Permission needed for the gateway's own admin endpoints
+pub const GATEWAY_ADMIN_PERMISSION: &str = "gateway:admin";
 
 /// Shared application state
 #[derive(Clone)]
@@ -23,11 +43,28 @@ pub struct AppState {
     pub router: Arc<IntelligentRouter>,
     pub temporal_client: Arc<ApiGatewayTemporalClient>,
     pub http_client: reqwest::Client,
+    /// For long-lived event streams, which the client timeout would cut off
+    pub stream_client: reqwest::Client,
     pub middleware_state: MiddlewareState,
+    pub openapi: Arc<OpenApiRegistry>,
+    /// None when the GraphQL endpoint is switched off
+    pub graphql_schema: Option<GatewaySchema>,
+    /// Circuit breakers and bulkheads guarding calls to the services
+    pub upstreams: Arc<UpstreamRegistry>,
+    /// None when gRPC passthrough is switched off
+    pub grpc: Option<Arc<GrpcRouter>>,
+    /// None when no webhook integrations are configured
+    pub webhooks: Option<Arc<WebhookReceiver>>,
+    /// None when no service has a mirror target
+    pub mirror: Option<Arc<TrafficMirror>>,
+    /// Endpoint pools the router picks services' endpoints from
+    pub discovery: Arc<ServiceDiscovery>,
+    /// None when maintenance mode is switched off
+    pub maintenance: Option<Arc<MaintenanceControl>>,
 }
 
 /// Health check response
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct HealthResponse {
     pub status: String,
     pub version: String,
@@ -35,12 +72,21 @@ pub struct HealthResponse {
     pub services: HashMap<String, ServiceHealth>,
 }
 
-#[derive(Serialize)]
+#[derive(Serialize, ToSchema)]
 pub struct ServiceHealth {
     pub status: String,
     pub response_time_ms: Option<u64>,
 }
 
+/// Circuit and bulkhead state of every service the gateway routes to
+#[derive(Serialize)]
+pub struct UpstreamHealthResponse {
+    /// "degraded" while any circuit isn't closed
+    pub status: String,
+    pub timestamp: chrono::DateTime<chrono::Utc>,
+    pub upstreams: Vec<UpstreamStatus>,
+}
+
 /// Workflow request payload
 #[derive(Deserialize)]
 pub struct WorkflowRequest {
@@ -48,14 +94,30 @@ pub struct WorkflowRequest {
     pub payload: Value,
 }
 
+/// Response to an operation started as an asynchronous workflow
+#[derive(Serialize, ToSchema)]
+pub struct WorkflowAccepted {
+    pub operation_id: String,
+    pub status_url: String,
+    pub stream_url: Option<String>,
+    pub estimated_duration_seconds: Option<u64>,
+}
+
 /// Query parameters for workflow status
-#[derive(Deserialize)]
+#[derive(Deserialize, IntoParams)]
+#[into_params(parameter_in = Query)]
 pub struct WorkflowStatusQuery {
     pub include_history: Option<bool>,
     pub include_progress: Option<bool>,
 }
 
 /// Health check handler
+#[utoipa::path(
+    get,
+    path = "/health",
+    tag = "gateway",
+    responses((status = 200, description = "Health of the gateway and downstream services", body = HealthResponse))
+)]
 pub async fn health_handler(State(state): State<AppState>) -> ApiResult<Json<HealthResponse>> {
     let start_time = std::time::Instant::now();
     
@@ -73,6 +135,9 @@ pub async fn health_handler(State(state): State<AppState>) -> ApiResult<Json<Hea
         ("tenant", &state.config.services.tenant_service),
         ("file", &state.config.services.file_service),
         ("workflow", &state.config.services.workflow_service),
+        ("module", &state.config.services.module_service),
+        ("license", &state.config.services.license_service),
+        ("security", &state.config.services.security_service),
     ] {
         let service_health = check_service_health(&state.http_client, service_config).await;
         services.insert(service_name.to_string(), service_health);
@@ -101,6 +166,11 @@ pub async fn handle_request(
     let method = request.method().clone();
     let path = request.uri().path().to_string();
     
+    // gRPC calls go to the service serving their package as they are
+    if let Some(grpc) = state.grpc.as_ref().filter(|_| GrpcRouter::is_grpc(request.headers())) {
+        return Ok(grpc.forward(&state.upstreams, request).await);
+    }
+    
     // Get request context
     let context = request.extensions().get::<RequestContext>().cloned()
         .unwrap_or_else(RequestContext::new);
@@ -144,6 +214,13 @@ async fn handle_direct_operation(
     let service_route = state.router.get_service_route(&operation, path)?;
     let target_url = state.router.build_service_url(&service_route, path);
     
+    // Refused without calling the service while its circuit is open or it's
+    // at capacity, with the service's fallback if one is configured
+    let permit = match state.upstreams.acquire(&service_route.service_name).await {
+        Ok(permit) => permit,
+        Err(rejection) => return Ok(rejection.into_response()),
+    };
+    
     // Extract all needed information before consuming request
     let method_str = request.method().as_str().to_string();
     let headers = request.headers().clone();
@@ -160,25 +237,49 @@ async fn handle_direct_operation(
             message: format!("Invalid HTTP method: {}", e),
         })?;
     
-    let mut downstream_request = state.http_client
-        .request(reqwest_method, &target_url)
-        .timeout(state.config.service_timeout(&service_route.service_name));
-    
-    // Forward headers (excluding hop-by-hop headers)
-    for (name, value) in &headers {
-        if !is_hop_by_hop_header(name.as_str()) {
-            if let Ok(value_str) = value.to_str() {
-                downstream_request = downstream_request.header(name.as_str(), value_str);
-            }
-        }
+    // Forward headers (excluding hop-by-hop headers, and the client's trace
+    // context, which continues in the gateway's own span)
+    let mut forwarded_headers: Vec<(String, String)> = headers
+        .iter()
+        .filter(|(name, _)| {
+            !is_hop_by_hop_header(name.as_str())
+                && !is_trace_context_header(name.as_str())
+                && name.as_str() != "x-forwarded-host"
+        })
+        .filter_map(|(name, value)| Some((name.as_str().to_string(), value.to_str().ok()?.to_string())))
+        .collect();
+
+    // The domain the client asked for, e.g. for hosted login page branding
+    if let Some(host) = headers.get(axum::http::header::HOST).and_then(|h| h.to_str().ok()) {
+        forwarded_headers.push(("X-Forwarded-Host".to_string(), host.to_string()));
     }
     
     // Add request ID for tracing
-    downstream_request = downstream_request.header("X-Request-ID", &context.request_id);
+    forwarded_headers.push(("X-Request-ID".to_string(), context.request_id.clone()));
     
     // Add tenant context if available
     if let Some(tenant_context) = &context.tenant_context {
-        downstream_request = downstream_request.header("X-Tenant-ID", &tenant_context.tenant_id);
+        forwarded_headers.push(("X-Tenant-ID".to_string(), tenant_context.tenant_id.clone()));
+    }
+    
+    // A sample of the service's requests goes to its mirror target too,
+    // once the client has its response
+    let shadow_request = state.mirror.as_ref()
+        .filter(|mirror| mirror.should_mirror(&service_route.service_name, &method_str, &context.request_id))
+        .map(|_| ShadowRequest {
+            request_id: context.request_id.clone(),
+            method: reqwest_method.clone(),
+            path: target_url.strip_prefix(service_route.base_url.as_str()).unwrap_or(path).to_string(),
+            headers: forwarded_headers.clone(),
+            body: body_bytes.clone(),
+        });
+    
+    let mut downstream_request = state.http_client
+        .request(reqwest_method, &target_url)
+        .timeout(state.config.service_timeout(&service_route.service_name))
+        .with_trace_context();
+    for (name, value) in &forwarded_headers {
+        downstream_request = downstream_request.header(name.as_str(), value.as_str());
     }
     
     // Add body if present
@@ -188,9 +289,11 @@ async fn handle_direct_operation(
     
     // Execute request
     let start_time = std::time::Instant::now();
-    let response = downstream_request.send().await
-        .map_err(|e| {
-            if e.is_timeout() {
+    let response = match downstream_request.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            permit.failure();
+            return Err(if e.is_timeout() {
                 ApiGatewayError::ServiceTimeout {
                     service: service_route.service_name.clone(),
                 }
@@ -198,8 +301,10 @@ async fn handle_direct_operation(
                 ApiGatewayError::ServiceUnavailable {
                     service: service_route.service_name.clone(),
                 }
-            }
-        })?;
+            });
+        }
+    };
+    permit.record_status(response.status().as_u16());
     
     let duration = start_time.elapsed();
     
@@ -219,6 +324,18 @@ async fn handle_direct_operation(
             message: format!("Failed to read response body: {}", e),
         })?;
     
+    if let (Some(mirror), Some(shadow_request)) = (&state.mirror, shadow_request) {
+        mirror.mirror(
+            &service_route.service_name,
+            shadow_request,
+            PrimaryResponse {
+                status: status_code,
+                body: body.clone(),
+                latency: duration,
+            },
+        );
+    }
+    
     let axum_status = axum::http::StatusCode::from_u16(status_code)
         .map_err(|e| ApiGatewayError::InternalError {
             message: format!("Invalid status code: {}", e),
@@ -259,6 +376,12 @@ async fn handle_workflow_operation(
     // Get workflow route
     let workflow_route = state.router.get_workflow_route(&operation)?;
     
+    // Validated by the idempotency middleware when it's switched on
+    let idempotency_key = request.headers()
+        .get(IDEMPOTENCY_KEY_HEADER)
+        .map(idempotency::validate_key)
+        .transpose()?;
+    
     // Extract request body as JSON
     let body_bytes = axum::body::to_bytes(request.into_body(), usize::MAX).await
         .map_err(|e| ApiGatewayError::InvalidRequest {
@@ -284,18 +407,36 @@ async fn handle_workflow_operation(
         .map(|u| u.user_id.as_str())
         .unwrap_or("anonymous");
     
+    // Retries with the same idempotency key get the same workflow id, so
+    // Temporal refuses to start the workflow twice even once the stored
+    // response has expired
+    let workflow_id = idempotency_key
+        .map(|key| idempotency::workflow_id(&workflow_route.workflow_type, tenant_id, user_id, &key))
+        .unwrap_or_else(|| format!("{}-{}-{}", workflow_route.workflow_type, tenant_id, uuid::Uuid::new_v4()));
+    
+    // Long-running workflows count against the tenant's concurrent workflow
+    // limit until they finish
+    let holds_slot = !workflow_route.is_synchronous;
+    if holds_slot {
+        state.middleware_state.rate_limiter.acquire_workflow_slot(tenant_id, &workflow_id).await?;
+    }
+    
     // Start workflow execution
     let start_time = std::time::Instant::now();
     let workflow_response = state.temporal_client
         .start_workflow(
             &workflow_route.workflow_type,
-            None, // Let the client generate workflow ID
+            Some(workflow_id.clone()),
             &workflow_route.task_queue,
             workflow_input,
             tenant_id,
             user_id,
         )
-        .await?;
+        .await;
+    if holds_slot && !matches!(workflow_response, Ok(WorkflowExecutionResponse::Asynchronous { .. })) {
+        state.middleware_state.rate_limiter.release_workflow_slot(tenant_id, &workflow_id).await;
+    }
+    let workflow_response = workflow_response?;
     
     let duration = start_time.elapsed();
     
@@ -330,12 +471,12 @@ async fn handle_workflow_operation(
                 "Asynchronous workflow started"
             );
             
-            let response_body = serde_json::json!({
-                "operation_id": operation_id,
-                "status_url": status_url,
-                "stream_url": stream_url,
-                "estimated_duration_seconds": estimated_duration_seconds
-            });
+            let response_body = WorkflowAccepted {
+                operation_id,
+                status_url,
+                stream_url,
+                estimated_duration_seconds,
+            };
             
             let mut response = Json(response_body).into_response();
             *response.status_mut() = StatusCode::ACCEPTED;
@@ -346,10 +487,21 @@ async fn handle_workflow_operation(
 }
 
 /// Get workflow status handler
+#[utoipa::path(
+    get,
+    path = "/api/v1/workflows/{operation_id}/status",
+    tag = "workflows",
+    params(("operation_id" = String, Path, description = "Operation id returned when the workflow started"), WorkflowStatusQuery),
+    responses(
+        (status = 200, description = "Status and progress of the workflow", body = Object),
+        (status = 404, description = "No such workflow")
+    )
+)]
 pub async fn get_workflow_status(
     State(state): State<AppState>,
     Path(operation_id): Path<String>,
     Query(query): Query<WorkflowStatusQuery>,
+    context: Option<axum::Extension<RequestContext>>,
 ) -> ApiResult<Json<Value>> {
     debug!(
         operation_id = %operation_id,
@@ -362,6 +514,13 @@ pub async fn get_workflow_status(
         .get_workflow_status(&operation_id)
         .await?;
     
+    // Finished workflows stop counting against the tenant's limit
+    if !matches!(status_response.status, WorkflowStatus::Pending | WorkflowStatus::Running) {
+        if let Some(tenant_id) = context_tenant_id(context) {
+            state.middleware_state.rate_limiter.release_workflow_slot(&tenant_id, &operation_id).await;
+        }
+    }
+    
     let mut response = serde_json::to_value(&status_response)
         .map_err(|e| ApiGatewayError::InternalError {
             message: format!("Failed to serialize workflow status: {}", e),
@@ -381,9 +540,21 @@ pub async fn get_workflow_status(
 }
 
 /// Cancel workflow handler
+#[utoipa::path(
+    post,
+    path = "/api/v1/workflows/{operation_id}/cancel",
+    tag = "workflows",
+    params(("operation_id" = String, Path, description = "Operation id returned when the workflow started")),
+    request_body(content = Object, description = "Optional `reason` for the cancellation"),
+    responses(
+        (status = 200, description = "Workflow cancelled", body = Object),
+        (status = 404, description = "No such workflow")
+    )
+)]
 pub async fn cancel_workflow(
     State(state): State<AppState>,
     Path(operation_id): Path<String>,
+    context: Option<axum::Extension<RequestContext>>,
     Json(payload): Json<Value>,
 ) -> ApiResult<Json<Value>> {
     let reason = payload.get("reason")
@@ -399,6 +570,9 @@ pub async fn cancel_workflow(
     state.temporal_client
         .cancel_workflow(&operation_id, reason)
         .await?;
+    if let Some(tenant_id) = context_tenant_id(context) {
+        state.middleware_state.rate_limiter.release_workflow_slot(&tenant_id, &operation_id).await;
+    }
     
     let response = serde_json::json!({
         "operation_id": operation_id,
@@ -410,34 +584,476 @@ pub async fn cancel_workflow(
     Ok(Json(response))
 }
 
-/// Signal workflow handler
-pub async fn signal_workflow(
+/// Signal, query and interaction listing for running workflows. These are
+/// forwarded to workflow-service, which validates the payload and checks the
+/// caller's roles before talking to Temporal.
+pub async fn workflow_interaction(
     State(state): State<AppState>,
-    Path((operation_id, signal_name)): Path<(String, String)>,
-    Json(payload): Json<Value>,
-) -> ApiResult<Json<Value>> {
+    request: Request,
+) -> Result<Response, ApiGatewayError> {
+    let context = request.extensions().get::<RequestContext>().cloned()
+        .unwrap_or_else(RequestContext::new);
+    let path_and_query = request.uri().path_and_query()
+        .map(|p| p.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+    let target_url = format!("{}{}", state.router.service_route("workflow")?.base_url, path_and_query);
+    
     debug!(
-        operation_id = %operation_id,
-        signal_name = %signal_name,
-        "Sending signal to workflow"
+        target_url = %target_url,
+        request_id = %context.request_id,
+        "Forwarding workflow interaction to workflow service"
     );
     
-    state.temporal_client
-        .signal_workflow(&operation_id, &signal_name, payload.clone())
-        .await?;
+    let reqwest_method = reqwest::Method::from_bytes(request.method().as_str().as_bytes())
+        .map_err(|e| ApiGatewayError::InternalError {
+            message: format!("Invalid HTTP method: {}", e),
+        })?;
+    let body_bytes = axum::body::to_bytes(request.into_body(), usize::MAX).await
+        .map_err(|e| ApiGatewayError::InvalidRequest {
+            message: format!("Failed to read request body: {}", e),
+        })?;
     
-    let response = serde_json::json!({
-        "operation_id": operation_id,
-        "signal_name": signal_name,
-        "signal_sent_at": chrono::Utc::now(),
-        "payload": payload
-    });
+    let permit = match state.upstreams.acquire("workflow").await {
+        Ok(permit) => permit,
+        Err(rejection) => return Ok(rejection.into_response()),
+    };
     
-    Ok(Json(response))
+    let mut downstream_request = state.http_client
+        .request(reqwest_method, &target_url)
+        .timeout(state.config.service_timeout("workflow"))
+        .with_trace_context()
+        .header("Content-Type", "application/json")
+        .header("X-Request-ID", &context.request_id);
+    
+    // Identity comes from the verified token only, never from client headers,
+    // since workflow-service authorizes signals by these roles
+    if let Some(tenant_context) = &context.tenant_context {
+        downstream_request = downstream_request.header("X-Tenant-ID", &tenant_context.tenant_id);
+    }
+    if let Some(user_context) = &context.user_context {
+        downstream_request = downstream_request
+            .header("X-User-ID", &user_context.user_id)
+            .header("X-User-Roles", user_context.roles.join(","));
+    }
+    if !body_bytes.is_empty() {
+        downstream_request = downstream_request.body(body_bytes);
+    }
+    
+    let response = match downstream_request.send().await {
+        Ok(response) => response,
+        Err(e) => {
+            permit.failure();
+            return Err(if e.is_timeout() {
+                ApiGatewayError::ServiceTimeout { service: "workflow".to_string() }
+            } else {
+                ApiGatewayError::ServiceUnavailable { service: "workflow".to_string() }
+            });
+        }
+    };
+    permit.record_status(response.status().as_u16());
+    
+    let status = StatusCode::from_u16(response.status().as_u16())
+        .map_err(|e| ApiGatewayError::InternalError {
+            message: format!("Invalid status code: {}", e),
+        })?;
+    let body = response.bytes().await
+        .map_err(|e| ApiGatewayError::InternalError {
+            message: format!("Failed to read response body: {}", e),
+        })?;
+    
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "application/json")
+        .body(axum::body::Body::from(body))
+        .map_err(|e| ApiGatewayError::InternalError {
+            message: format!("Failed to build response: {}", e),
+        })
+}
+
+/// Answer an ACME HTTP-01 challenge from white-label-service. Challenges
+/// are requested on the custom domain itself, over plain HTTP, before the
+/// domain has a certificate.
+pub async fn acme_challenge_handler(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> Result<Response, ApiGatewayError> {
+    let target_url = format!(
+        "{}/api/v1/white-label/acme-challenge/{}",
+        state.router.service_route("white_label")?.base_url, token
+    );
+
+    let response = state.http_client
+        .get(&target_url)
+        .timeout(state.config.service_timeout("white_label"))
+        .with_trace_context()
+        .send()
+        .await
+        .map_err(|e| {
+            if e.is_timeout() {
+                ApiGatewayError::ServiceTimeout { service: "white_label".to_string() }
+            } else {
+                ApiGatewayError::ServiceUnavailable { service: "white_label".to_string() }
+            }
+        })?;
+
+    let status = StatusCode::from_u16(response.status().as_u16())
+        .map_err(|e| ApiGatewayError::InternalError {
+            message: format!("Invalid status code: {}", e),
+        })?;
+    let body = response.bytes().await
+        .map_err(|e| ApiGatewayError::InternalError {
+            message: format!("Failed to read response body: {}", e),
+        })?;
+
+    Response::builder()
+        .status(status)
+        .header("Content-Type", "text/plain")
+        .body(axum::body::Body::from(body))
+        .map_err(|e| ApiGatewayError::InternalError {
+            message: format!("Failed to build response: {}", e),
+        })
+}
+
+/// Combined OpenAPI document of the gateway and the services behind it,
+/// without the operations the caller's tenant is not entitled to
+pub async fn openapi_handler(
+    State(state): State<AppState>,
+    request: Request,
+) -> Response {
+    let tenant_id = request.extensions()
+        .get::<RequestContext>()
+        .and_then(|context| context.tenant_context.as_ref())
+        .map(|tenant| tenant.tenant_id.clone());
+    
+    let document = state.openapi.for_tenant(tenant_id.as_deref()).await;
+    
+    // Filtered per tenant, so shared caches must not keep it
+    ([(header::CACHE_CONTROL, "private, max-age=60")], Json(document)).into_response()
+}
+
+/// Read-only GraphQL queries over users, tenants, files and workflow
+/// status, resolved against the services as the caller
+pub async fn graphql_handler(
+    State(state): State<AppState>,
+    context: Option<axum::Extension<RequestContext>>,
+    headers: axum::http::HeaderMap,
+    request: async_graphql_axum::GraphQLRequest,
+) -> Response {
+    let Some(schema) = state.graphql_schema.clone() else {
+        return StatusCode::NOT_FOUND.into_response();
+    };
+    let context = context.map(|axum::Extension(context)| context).unwrap_or_else(RequestContext::new);
+    
+    let backend = graphql::Backend::new(
+        state.http_client.clone(),
+        state.router.clone(),
+        state.upstreams.clone(),
+        state.config.clone(),
+        &headers,
+        &context,
+    );
+    let request = graphql::request_data(request.into_inner(), backend, state.temporal_client.clone(), context);
+    
+    async_graphql_axum::GraphQLResponse::from(schema.execute(request).await).into_response()
+}
+
+/// WebSocket connection for subscriptions to workflow progress and service
+/// event streams, in place of polling
+pub async fn websocket_handler(
+    State(state): State<AppState>,
+    context: Option<axum::Extension<RequestContext>>,
+    headers: axum::http::HeaderMap,
+    uri: axum::http::Uri,
+    upgrade: axum::extract::ws::WebSocketUpgrade,
+) -> Response {
+    let context = context.map(|axum::Extension(context)| context).unwrap_or_else(RequestContext::new);
+    
+    let Some(session) = websocket::Session::new(
+        state.router.clone(),
+        state.stream_client.clone(),
+        state.temporal_client.clone(),
+        state.config.websocket.clone(),
+        &headers,
+        &uri,
+        &context,
+    ) else {
+        return ApiGatewayError::AuthenticationRequired.into_response();
+    };
+    
+    upgrade
+        .max_message_size(websocket::MAX_CLIENT_MESSAGE_BYTES)
+        .on_upgrade(move |socket| websocket::serve(socket, session))
+}
+
+/// Circuit and bulkhead state per service, so operators can see which
+/// service is being shed before its errors show up elsewhere
+pub async fn upstream_health_handler(State(state): State<AppState>) -> Json<UpstreamHealthResponse> {
+    let upstreams = state.upstreams.snapshot();
+    let degraded = upstreams
+        .iter()
+        .any(|upstream| upstream.state != crate::upstreams::CircuitState::Closed);
+    
+    Json(UpstreamHealthResponse {
+        status: if degraded { "degraded" } else { "healthy" }.to_string(),
+        timestamp: chrono::Utc::now(),
+        upstreams,
+    })
+}
+
+/// A tenant's rate limit tier, request counters and running workflows
+pub async fn get_tenant_rate_limits(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<String>,
+    context: Option<axum::Extension<RequestContext>>,
+) -> ApiResult<Json<TenantRateLimitStatus>> {
+    require_gateway_admin(context)?;
+    
+    let status = state.middleware_state.rate_limiter.tenant_status(&tenant_id).await?;
+    Ok(Json(status))
+}
+
+/// Clear a tenant's request counters, e.g. after raising its plan
+pub async fn reset_tenant_rate_limits(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<String>,
+    context: Option<axum::Extension<RequestContext>>,
+) -> ApiResult<Json<Value>> {
+    require_gateway_admin(context)?;
+    
+    let counters_reset = state.middleware_state.rate_limiter.reset_tenant_rate_limits(&tenant_id).await?;
+    info!(tenant_id = %tenant_id, counters_reset = counters_reset, "Tenant rate limits reset");
+    
+    Ok(Json(serde_json::json!({
+        "tenant_id": tenant_id,
+        "counters_reset": counters_reset,
+        "reset_at": chrono::Utc::now()
+    })))
+}
+
+/// Deliveries from a third-party integration, verified by the integration's
+/// signature scheme rather than a bearer token
+pub async fn webhook_handler(
+    State(state): State<AppState>,
+    Path(integration): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> ApiResult<Json<WebhookReceipt>> {
+    let receiver = webhook_receiver(&state, &integration)?;
+    let receipt = receiver.receive(&integration, &headers, &body).await?;
+    Ok(Json(receipt))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookDeliveriesQuery {
+    pub limit: Option<usize>,
+}
+
+/// An integration's most recently archived deliveries (admin only)
+pub async fn list_webhook_deliveries(
+    State(state): State<AppState>,
+    Path(integration): Path<String>,
+    Query(query): Query<WebhookDeliveriesQuery>,
+    context: Option<axum::Extension<RequestContext>>,
+) -> ApiResult<Json<Vec<ArchivedDelivery>>> {
+    require_gateway_admin(context)?;
+    
+    let receiver = webhook_receiver(&state, &integration)?;
+    let deliveries = receiver.list(&integration, query.limit.unwrap_or(50).min(500)).await?;
+    Ok(Json(deliveries))
+}
+
+/// Dispatch an archived delivery again (admin only)
+pub async fn replay_webhook_delivery(
+    State(state): State<AppState>,
+    Path((integration, delivery_id)): Path<(String, String)>,
+    context: Option<axum::Extension<RequestContext>>,
+) -> ApiResult<Json<ArchivedDelivery>> {
+    require_gateway_admin(context)?;
+    
+    let receiver = webhook_receiver(&state, &integration)?;
+    let delivery = receiver.replay(&integration, &delivery_id).await?;
+    Ok(Json(delivery))
+}
+
+/// Shadow traffic counters and recent mismatches per mirrored service (admin only)
+pub async fn mirror_status_handler(
+    State(state): State<AppState>,
+    context: Option<axum::Extension<RequestContext>>,
+) -> ApiResult<Json<Vec<MirrorStatus>>> {
+    require_gateway_admin(context)?;
+    
+    Ok(Json(state.mirror.as_ref().map(|mirror| mirror.snapshot()).unwrap_or_default()))
+}
+
+/// Endpoint pools and their health per service (admin only)
+pub async fn discovery_handler(
+    State(state): State<AppState>,
+    context: Option<axum::Extension<RequestContext>>,
+) -> ApiResult<Json<Vec<ServicePool>>> {
+    require_gateway_admin(context)?;
+    
+    Ok(Json(state.discovery.snapshot()))
+}
+
+/// Look services up and check their endpoints now, rather than at the next
+/// interval (admin only)
+pub async fn refresh_discovery(
+    State(state): State<AppState>,
+    context: Option<axum::Extension<RequestContext>>,
+) -> ApiResult<Json<DiscoveryRefresh>> {
+    require_gateway_admin(context)?;
+    
+    let refreshed = state.discovery.refresh().await;
+    if state.config.discovery.health_check_seconds > 0 {
+        state.discovery.check_health().await;
+    }
+    info!(changed = ?refreshed.changed, failed = ?refreshed.failed, "Service discovery refreshed on request");
+    Ok(Json(refreshed))
+}
+
+/// Maintenance windows, how many workflows each is still waiting on, and
+/// route kill switches (admin only)
+pub async fn maintenance_status_handler(
+    State(state): State<AppState>,
+    context: Option<axum::Extension<RequestContext>>,
+) -> ApiResult<Json<MaintenanceStatus>> {
+    require_gateway_admin(context)?;
+    
+    Ok(Json(maintenance_control(&state)?.status().await))
+}
+
+/// Put the whole platform into maintenance (admin only)
+pub async fn enable_platform_maintenance(
+    State(state): State<AppState>,
+    context: Option<axum::Extension<RequestContext>>,
+    Json(request): Json<EnableMaintenanceRequest>,
+) -> ApiResult<Json<MaintenanceWindow>> {
+    let enabled_by = context_user_id(&context);
+    require_gateway_admin(context)?;
+    
+    let window = maintenance_control(&state)?.enable(None, request, enabled_by).await?;
+    Ok(Json(window))
+}
+
+/// End the platform-wide window (admin only)
+pub async fn disable_platform_maintenance(
+    State(state): State<AppState>,
+    context: Option<axum::Extension<RequestContext>>,
+) -> ApiResult<Json<Value>> {
+    require_gateway_admin(context)?;
+    
+    let removed = maintenance_control(&state)?.disable(None).await?;
+    Ok(Json(serde_json::json!({ "removed": removed })))
+}
+
+/// Put one tenant into maintenance, e.g. for a data migration (admin only)
+pub async fn enable_tenant_maintenance(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<String>,
+    context: Option<axum::Extension<RequestContext>>,
+    Json(request): Json<EnableMaintenanceRequest>,
+) -> ApiResult<Json<MaintenanceWindow>> {
+    let enabled_by = context_user_id(&context);
+    require_gateway_admin(context)?;
+    
+    let window = maintenance_control(&state)?.enable(Some(&tenant_id), request, enabled_by).await?;
+    Ok(Json(window))
+}
+
+/// End a tenant's window (admin only)
+pub async fn disable_tenant_maintenance(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<String>,
+    context: Option<axum::Extension<RequestContext>>,
+) -> ApiResult<Json<Value>> {
+    require_gateway_admin(context)?;
+    
+    let removed = maintenance_control(&state)?.disable(Some(&tenant_id)).await?;
+    Ok(Json(serde_json::json!({
+        "tenant_id": tenant_id,
+        "removed": removed
+    })))
+}
+
+/// Switch a route off, or change an existing switch (admin only)
+pub async fn set_kill_switch(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    context: Option<axum::Extension<RequestContext>>,
+    Json(request): Json<KillSwitchRequest>,
+) -> ApiResult<Json<KillSwitch>> {
+    let enabled_by = context_user_id(&context);
+    require_gateway_admin(context)?;
+    
+    let switch = maintenance_control(&state)?.set_kill_switch(&name, request, enabled_by).await?;
+    Ok(Json(switch))
+}
+
+/// Switch a route back on (admin only)
+pub async fn remove_kill_switch(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    context: Option<axum::Extension<RequestContext>>,
+) -> ApiResult<Json<Value>> {
+    require_gateway_admin(context)?;
+    
+    let removed = maintenance_control(&state)?.remove_kill_switch(&name).await?;
+    Ok(Json(serde_json::json!({
+        "name": name,
+        "removed": removed
+    })))
 }
 
 /// Helper functions
 
+fn maintenance_control(state: &AppState) -> ApiResult<&MaintenanceControl> {
+    state
+        .maintenance
+        .as_deref()
+        .ok_or_else(|| ApiGatewayError::InvalidRequest {
+            message: "Maintenance mode is not enabled on this gateway".to_string(),
+        })
+}
+
+fn context_user_id(context: &Option<axum::Extension<RequestContext>>) -> Option<String> {
+    context
+        .as_ref()
+        .and_then(|context| context.user_context.as_ref())
+        .map(|user| user.user_id.clone())
+}
+
+
+fn webhook_receiver<'a>(state: &'a AppState, integration: &str) -> ApiResult<&'a WebhookReceiver> {
+    state
+        .webhooks
+        .as_deref()
+        .ok_or_else(|| ApiGatewayError::WebhookIntegrationNotFound {
+            integration: integration.to_string(),
+        })
+}
+
+fn context_tenant_id(context: Option<axum::Extension<RequestContext>>) -> Option<String> {
+    context.and_then(|axum::Extension(context)| context.tenant_context.map(|tenant| tenant.tenant_id))
+}
+
+fn require_gateway_admin(context: Option<axum::Extension<RequestContext>>) -> ApiResult<()> {
+    let is_admin = context
+        .and_then(|axum::Extension(context)| context.user_context)
+        .is_some_and(|user| {
+            user.permissions
+                .iter()
+                .any(|p| p == GATEWAY_ADMIN_PERMISSION || p == "*")
+        });
+    
+    if is_admin {
+        Ok(())
+    } else {
+        Err(ApiGatewayError::InsufficientPermissions {
+            required_permission: GATEWAY_ADMIN_PERMISSION.to_string(),
+        })
+    }
+}
+
 async fn check_temporal_health(_temporal_client: &ApiGatewayTemporalClient) -> ServiceHealth {
     // For now, assume Temporal is healthy if client exists
     // This should be replaced with actual health check when SDK is stable
@@ -489,6 +1105,10 @@ fn is_hop_by_hop_header(name: &str) -> bool {
     )
 }
 
+fn is_trace_context_header(name: &str) -> bool {
+    matches!(name.to_lowercase().as_str(), "traceparent" | "tracestate")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -501,4 +1121,11 @@ mod tests {
         assert!(!is_hop_by_hop_header("Content-Type"));
         assert!(!is_hop_by_hop_header("Authorization"));
     }
+
+    #[test]
+    fn test_trace_context_header_detection() {
+        assert!(is_trace_context_header("traceparent"));
+        assert!(is_trace_context_header("TraceState"));
+        assert!(!is_trace_context_header("X-Request-ID"));
+    }
 }
\ No newline at end of file
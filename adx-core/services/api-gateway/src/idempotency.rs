@@ -0,0 +1,357 @@
+//! `Idempotency-Key` handling for mutating requests.
+//!
+//! The first request with a key claims it in Redis and runs; its response is
+//! stored under the key, and repeats of the same request within the TTL get
+//! the stored response back without reaching the services again. A repeat
+//! while the first is still running is refused, as is reusing a key for a
+//! different request. Keys are scoped to the caller's tenant and user.
+
+use axum::{
+    body::{Body, Bytes},
+    http::{HeaderMap, HeaderName, HeaderValue, Method, StatusCode},
+    response::{IntoResponse, Response},
+};
+use base64::Engine;
+use redis::{AsyncCommands, Client as RedisClient};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+use crate::config::IdempotencyConfig;
+use crate::error::{ApiGatewayError, ApiResult};
+
+pub const IDEMPOTENCY_KEY_HEADER: &str = "Idempotency-Key";
+
+/// Set on responses replayed from a stored one
+pub const REPLAYED_HEADER: &str = "Idempotent-Replayed";
+
+const MAX_KEY_LENGTH: usize = 255;
+
+/// Response headers that belong to the original exchange only
+const UNSTORED_HEADERS: &[&str] = &[
+    "x-request-id",
+    "set-cookie",
+    "date",
+    "connection",
+    "transfer-encoding",
+    "content-length",
+];
+
+/// Only methods that change something need protecting from retries
+pub fn applies_to(method: &Method) -> bool {
+    matches!(*method, Method::POST | Method::PUT | Method::PATCH | Method::DELETE)
+}
+
+/// Keys are opaque to the gateway, but must be printable and of bounded
+/// length since they end up in Redis keys and workflow ids
+pub fn validate_key(value: &HeaderValue) -> ApiResult<String> {
+    let key = value.to_str().map(str::trim).unwrap_or_default();
+    if key.is_empty() || key.len() > MAX_KEY_LENGTH || !key.chars().all(|c| c.is_ascii_graphic()) {
+        return Err(ApiGatewayError::InvalidRequest {
+            message: format!(
+                "{} must be 1 to {} printable characters",
+                IDEMPOTENCY_KEY_HEADER, MAX_KEY_LENGTH
+            ),
+        });
+    }
+    Ok(key.to_string())
+}
+
+/// What identifies "the same request" for a key
+pub fn fingerprint(method: &Method, path: &str, body: &[u8]) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(method.as_str().as_bytes());
+    hasher.update(b"\n");
+    hasher.update(path.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(body);
+    format!("{:x}", hasher.finalize())
+}
+
+/// Workflow id for a workflow started under an idempotency key. Retries
+/// that get past the response store, e.g. after it has expired, start a
+/// workflow with the same id, which Temporal refuses as already started.
+pub fn workflow_id(workflow_type: &str, tenant_id: &str, user_id: &str, key: &str) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(user_id.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(key.as_bytes());
+    let digest = format!("{:x}", hasher.finalize());
+    format!("{}-{}-idem-{}", workflow_type, tenant_id, &digest[..32])
+}
+
+/// A response kept for replay
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct StoredResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    /// Base64, since bodies needn't be UTF-8
+    pub body: String,
+}
+
+impl StoredResponse {
+    fn new(status: StatusCode, headers: &HeaderMap, body: &[u8]) -> Self {
+        let headers = headers
+            .iter()
+            .filter(|(name, _)| !UNSTORED_HEADERS.contains(&name.as_str()))
+            .filter_map(|(name, value)| {
+                value
+                    .to_str()
+                    .ok()
+                    .map(|value| (name.as_str().to_string(), value.to_string()))
+            })
+            .collect();
+
+        Self {
+            status: status.as_u16(),
+            headers,
+            body: base64::engine::general_purpose::STANDARD.encode(body),
+        }
+    }
+}
+
+impl IntoResponse for StoredResponse {
+    fn into_response(self) -> Response {
+        let body = base64::engine::general_purpose::STANDARD
+            .decode(&self.body)
+            .unwrap_or_default();
+        let mut response = Response::new(Body::from(body));
+        *response.status_mut() = StatusCode::from_u16(self.status).unwrap_or(StatusCode::OK);
+
+        for (name, value) in &self.headers {
+            if let (Ok(name), Ok(value)) = (
+                HeaderName::from_bytes(name.as_bytes()),
+                HeaderValue::from_str(value),
+            ) {
+                response.headers_mut().append(name, value);
+            }
+        }
+        response
+            .headers_mut()
+            .insert(REPLAYED_HEADER, HeaderValue::from_static("true"));
+        response
+    }
+}
+
+/// What's stored under a key
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "state", rename_all = "snake_case")]
+enum Record {
+    InProgress { fingerprint: String },
+    Completed { fingerprint: String, response: StoredResponse },
+}
+
+/// Outcome of claiming a key
+#[derive(Debug)]
+pub enum Claim {
+    /// The request is the key's first and should run
+    Acquired,
+    /// The request already ran; reply with its response
+    Replay(StoredResponse),
+}
+
+/// Redis-backed store of idempotency keys and their responses
+#[derive(Clone)]
+pub struct IdempotencyStore {
+    redis_client: Arc<RedisClient>,
+    config: IdempotencyConfig,
+}
+
+impl IdempotencyStore {
+    pub fn new(redis_url: &str, config: IdempotencyConfig) -> ApiResult<Self> {
+        let redis_client = RedisClient::open(redis_url).map_err(|e| ApiGatewayError::RedisError {
+            message: format!("Failed to create Redis client: {}", e),
+        })?;
+
+        Ok(Self {
+            redis_client: Arc::new(redis_client),
+            config,
+        })
+    }
+
+    /// Redis key for an idempotency key, scoped to who sent it
+    pub fn scoped_key(tenant_id: &str, user_id: &str, key: &str) -> String {
+        format!("idempotency:{}:{}:{}", tenant_id, user_id, key)
+    }
+
+    /// Claims `key` for a request, or finds what an earlier request with it
+    /// left behind
+    pub async fn claim(&self, key: &str, fingerprint: &str) -> ApiResult<Claim> {
+        let mut conn = self.redis_client.get_async_connection().await?;
+
+        let in_progress = serde_json::to_string(&Record::InProgress {
+            fingerprint: fingerprint.to_string(),
+        })
+        .map_err(|e| ApiGatewayError::InternalError {
+            message: format!("Failed to serialize idempotency record: {}", e),
+        })?;
+        let claimed: Option<String> = redis::cmd("SET")
+            .arg(key)
+            .arg(in_progress)
+            .arg("NX")
+            .arg("EX")
+            .arg(self.config.lock_seconds)
+            .query_async(&mut conn)
+            .await?;
+        if claimed.is_some() {
+            return Ok(Claim::Acquired);
+        }
+
+        let stored: Option<String> = conn.get(key).await?;
+        let Some(record) = stored.and_then(|stored| serde_json::from_str::<Record>(&stored).ok()) else {
+            // Expired between the two commands, or unreadable; a retry will
+            // claim it
+            return Err(ApiGatewayError::IdempotencyKeyInProgress {
+                key: key.to_string(),
+            });
+        };
+
+        match record {
+            Record::Completed { fingerprint: stored, response } if stored == fingerprint => {
+                Ok(Claim::Replay(response))
+            }
+            Record::InProgress { fingerprint: stored } if stored == fingerprint => {
+                Err(ApiGatewayError::IdempotencyKeyInProgress {
+                    key: key.to_string(),
+                })
+            }
+            _ => Err(ApiGatewayError::IdempotencyKeyReused {
+                key: key.to_string(),
+            }),
+        }
+    }
+
+    /// Stores the response of a request that claimed `key`, and hands it on.
+    /// Server errors and responses too large to store release the key
+    /// instead, so the request can be retried.
+    pub async fn complete(&self, key: &str, fingerprint: &str, response: Response) -> Response {
+        let storable = !response.status().is_server_error()
+            && axum::body::HttpBody::size_hint(response.body())
+                .exact()
+                .is_some_and(|size| size as usize <= self.config.max_response_bytes);
+        if !storable {
+            self.release(key).await;
+            return response;
+        }
+
+        let (parts, body) = response.into_parts();
+        let body: Bytes = match axum::body::to_bytes(body, self.config.max_response_bytes).await {
+            Ok(body) => body,
+            Err(e) => {
+                warn!(error = %e, "Failed to read response for idempotent replay");
+                self.release(key).await;
+                return ApiGatewayError::InternalError {
+                    message: "Failed to read response body".to_string(),
+                }
+                .into_response();
+            }
+        };
+
+        let record = Record::Completed {
+            fingerprint: fingerprint.to_string(),
+            response: StoredResponse::new(parts.status, &parts.headers, &body),
+        };
+        if let Err(e) = self.store(key, &record).await {
+            // The request ran; a retry will run it again rather than replay
+            warn!(error = %e, "Failed to store response for idempotent replay");
+            self.release(key).await;
+        }
+
+        Response::from_parts(parts, Body::from(body))
+    }
+
+    async fn store(&self, key: &str, record: &Record) -> ApiResult<()> {
+        let value = serde_json::to_string(record).map_err(|e| ApiGatewayError::InternalError {
+            message: format!("Failed to serialize idempotency record: {}", e),
+        })?;
+        let mut conn = self.redis_client.get_async_connection().await?;
+        conn.set_ex::<_, _, ()>(key, value, self.config.ttl_seconds).await?;
+        Ok(())
+    }
+
+    async fn release(&self, key: &str) {
+        let result = async {
+            let mut conn = self.redis_client.get_async_connection().await?;
+            conn.del::<_, ()>(key).await
+        }
+        .await;
+        if let Err(e) = result {
+            debug!(error = %e, "Failed to release idempotency key; it expires with its lock");
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_applies_to_mutating_methods() {
+        assert!(applies_to(&Method::POST));
+        assert!(applies_to(&Method::PATCH));
+        assert!(applies_to(&Method::DELETE));
+        assert!(!applies_to(&Method::GET));
+        assert!(!applies_to(&Method::OPTIONS));
+    }
+
+    #[test]
+    fn test_validate_key() {
+        assert_eq!(
+            validate_key(&HeaderValue::from_static(" order-42 ")).unwrap(),
+            "order-42"
+        );
+        assert!(validate_key(&HeaderValue::from_static("")).is_err());
+        assert!(validate_key(&HeaderValue::from_static("has space")).is_err());
+        assert!(validate_key(&HeaderValue::from_str(&"k".repeat(256)).unwrap()).is_err());
+    }
+
+    #[test]
+    fn test_fingerprint_covers_method_path_and_body() {
+        let base = fingerprint(&Method::POST, "/api/v1/files", b"{}");
+        assert_eq!(base, fingerprint(&Method::POST, "/api/v1/files", b"{}"));
+        assert_ne!(base, fingerprint(&Method::PUT, "/api/v1/files", b"{}"));
+        assert_ne!(base, fingerprint(&Method::POST, "/api/v1/users", b"{}"));
+        assert_ne!(base, fingerprint(&Method::POST, "/api/v1/files", b"{\"a\":1}"));
+    }
+
+    #[test]
+    fn test_workflow_id_is_stable_per_caller_and_key() {
+        let id = workflow_id("create_tenant", "tenant-1", "user-1", "key-1");
+        assert_eq!(id, workflow_id("create_tenant", "tenant-1", "user-1", "key-1"));
+        assert!(id.starts_with("create_tenant-tenant-1-idem-"));
+        assert_ne!(id, workflow_id("create_tenant", "tenant-1", "user-2", "key-1"));
+        assert_ne!(id, workflow_id("create_tenant", "tenant-1", "user-1", "key-2"));
+    }
+
+    #[test]
+    fn test_record_round_trip() {
+        let record = Record::Completed {
+            fingerprint: "abc".to_string(),
+            response: StoredResponse {
+                status: 202,
+                headers: vec![("content-type".to_string(), "application/json".to_string())],
+                body: "e30=".to_string(),
+            },
+        };
+        let json = serde_json::to_string(&record).unwrap();
+        assert!(json.contains("\"state\":\"completed\""));
+        assert_eq!(serde_json::from_str::<Record>(&json).unwrap(), record);
+    }
+
+    #[tokio::test]
+    async fn test_stored_response_replays_status_headers_and_body() {
+        let mut headers = HeaderMap::new();
+        headers.insert("content-type", HeaderValue::from_static("application/json"));
+        headers.insert("x-request-id", HeaderValue::from_static("original"));
+        let stored = StoredResponse::new(StatusCode::ACCEPTED, &headers, b"{\"ok\":true}");
+        assert!(stored.headers.iter().all(|(name, _)| name != "x-request-id"));
+
+        let response = stored.into_response();
+        assert_eq!(response.status(), StatusCode::ACCEPTED);
+        assert_eq!(response.headers()["content-type"], "application/json");
+        assert_eq!(response.headers()[REPLAYED_HEADER], "true");
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"{\"ok\":true}");
+    }
+}
@@ -1,11 +1,24 @@
 pub mod config;
+pub mod discovery;
 pub mod error;
+pub mod graphql;
+pub mod grpc;
 pub mod handlers;
+pub mod idempotency;
+pub mod maintenance;
 pub mod middleware;
+pub mod mirror;
+pub mod openapi;
 pub mod rate_limiter;
 pub mod routing;
 pub mod server;
 pub mod temporal_client;
+pub mod tls;
+pub mod transform;
+pub mod upstreams;
+pub mod validation;
+pub mod webhooks;
+pub mod websocket;
 
 pub use config::ApiGatewayConfig;
 pub use error::{ApiGatewayError, ApiResult};
@@ -1,7 +1,7 @@
 use anyhow::Result;
 use dotenvy::dotenv;
 use tracing::{info, error};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use adx_shared::telemetry::init_tracing;
 
 mod server;
 mod config;
@@ -10,7 +10,20 @@ mod middleware;
 mod handlers;
 mod temporal_client;
 mod rate_limiter;
+mod tls;
 mod error;
+mod openapi;
+mod graphql;
+mod websocket;
+mod upstreams;
+mod idempotency;
+mod transform;
+mod grpc;
+mod validation;
+mod webhooks;
+mod mirror;
+mod discovery;
+mod maintenance;
 
 use crate::server::ApiGatewayServer;
 use config::ApiGatewayConfig;
@@ -20,14 +33,8 @@ async fn main() -> Result<()> {
     // Load environment variables
     dotenv().ok();
     
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "api_gateway=debug,tower_http=debug".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    // Initialize tracing, exporting spans when OTEL_EXPORTER_OTLP_ENDPOINT is set
+    let _telemetry = init_tracing("api-gateway", "api_gateway=debug,tower_http=debug")?;
 
     info!("Starting ADX Core API Gateway (Temporal-First)");
 
@@ -0,0 +1,744 @@
+//! Maintenance mode and route kill switches.
+//!
+//! Operators put the whole platform, or one tenant, into maintenance ahead
+//! of a cutover. Until the window starts, only new workflows are refused, so
+//! the ones already running can finish; workflow-service reports how many
+//! are left. Once it starts, every request in its scope is refused with a
+//! 503 carrying the operator's message and, where the request's domain has
+//! one, the tenant's branding. Kill switches turn single routes off the same
+//! way, e.g. a misbehaving export endpoint, without touching the rest.
+//!
+//! Windows and switches live in Redis, so every gateway instance applies
+//! them; each instance re-reads them every `refresh_seconds`.
+
+use axum::{
+    body::Body,
+    http::{header, HeaderValue, Method, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use chrono::{DateTime, Utc};
+use redis::{AsyncCommands, Client as RedisClient};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{Duration, Instant};
+use tokio::task::JoinHandle;
+use tracing::{debug, info, warn};
+
+use crate::config::MaintenanceConfig;
+use crate::error::{ApiGatewayError, ApiResult};
+use crate::routing::{IntelligentRouter, OperationType};
+use crate::transform::path_matches;
+
+const WINDOWS_KEY: &str = "gateway:maintenance";
+const KILL_SWITCHES_KEY: &str = "gateway:kill-switches";
+/// Field of the platform-wide window; tenants' are `tenant:{id}`
+const PLATFORM_FIELD: &str = "platform";
+
+const DEFAULT_MESSAGE: &str = "We're carrying out scheduled maintenance and will be back shortly.";
+const DEFAULT_KILL_SWITCH_MESSAGE: &str = "This feature is temporarily unavailable.";
+
+/// Longest lead time between setting a window and its start
+const MAX_DRAIN_SECONDS: u64 = 7 * 86400;
+
+/// Branding lookups kept at most; requests carry arbitrary Host headers
+const MAX_BRANDING_ENTRIES: usize = 1000;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct MaintenanceWindow {
+    /// None for the whole platform
+    pub tenant_id: Option<String>,
+    pub message: String,
+    /// When requests start being refused. New workflows are refused from
+    /// the moment the window is set.
+    pub starts_at: DateTime<Utc>,
+    /// When the window is expected to end, sent as `Retry-After`
+    pub ends_at: Option<DateTime<Utc>>,
+    pub enabled_by: Option<String>,
+    pub enabled_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum MaintenancePhase {
+    /// New workflows are refused while running ones finish
+    Draining,
+    /// Requests are refused
+    Active,
+}
+
+impl MaintenanceWindow {
+    pub fn phase(&self, now: DateTime<Utc>) -> MaintenancePhase {
+        if now < self.starts_at {
+            MaintenancePhase::Draining
+        } else {
+            MaintenancePhase::Active
+        }
+    }
+
+    fn retry_after(&self, now: DateTime<Utc>) -> Option<u64> {
+        let ends_at = self.ends_at?;
+        Some((ends_at - now).num_seconds().max(1) as u64)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EnableMaintenanceRequest {
+    pub message: Option<String>,
+    /// How long new workflows are refused before requests are; the window
+    /// starts at once without it
+    #[serde(default)]
+    pub drain_seconds: u64,
+    pub ends_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct KillSwitch {
+    pub name: String,
+    /// `*` matches one path segment and a trailing `**` the rest of the path
+    pub path: String,
+    /// Methods switched off; every method when empty
+    #[serde(default)]
+    pub methods: Vec<String>,
+    pub message: String,
+    pub enabled_by: Option<String>,
+    pub enabled_at: DateTime<Utc>,
+}
+
+impl KillSwitch {
+    fn applies_to(&self, method: &Method, path: &str) -> bool {
+        (self.methods.is_empty() || self.methods.iter().any(|m| m.eq_ignore_ascii_case(method.as_str())))
+            && path_matches(&self.path, path)
+    }
+}
+
+#[derive(Debug, Deserialize)]
+pub struct KillSwitchRequest {
+    pub path: String,
+    #[serde(default)]
+    pub methods: Vec<String>,
+    pub message: Option<String>,
+}
+
+/// A window as reported by `/gateway/maintenance`
+#[derive(Debug, Serialize)]
+pub struct WindowStatus {
+    #[serde(flatten)]
+    pub window: MaintenanceWindow,
+    pub phase: MaintenancePhase,
+    /// Workflows still running in the window's scope, per workflow-service;
+    /// None when it couldn't be asked
+    pub in_flight_workflows: Option<u64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct MaintenanceStatus {
+    pub platform: Option<WindowStatus>,
+    pub tenants: Vec<WindowStatus>,
+    pub kill_switches: Vec<KillSwitch>,
+}
+
+/// A tenant's published theme, as far as the maintenance page uses it
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct Branding {
+    pub brand_name: Option<String>,
+    pub logo_url: Option<String>,
+    pub primary_color: Option<String>,
+}
+
+impl Branding {
+    fn from_published(published: &Value) -> Self {
+        let string = |pointer: &str| published.pointer(pointer).and_then(Value::as_str).map(str::to_string);
+        Self {
+            brand_name: string("/brand_name"),
+            logo_url: string("/logo_url"),
+            primary_color: string("/color_scheme/primary_color").filter(|color| is_css_color(color)),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+struct Snapshot {
+    platform: Option<MaintenanceWindow>,
+    tenants: HashMap<String, MaintenanceWindow>,
+    kill_switches: Vec<KillSwitch>,
+}
+
+impl Snapshot {
+    /// Why a request is refused, if it is
+    fn refusal(
+        &self,
+        router: &IntelligentRouter,
+        method: &Method,
+        path: &str,
+        tenant_id: Option<&str>,
+        now: DateTime<Utc>,
+    ) -> Option<ApiGatewayError> {
+        if let Some(switch) = self.kill_switches.iter().find(|switch| switch.applies_to(method, path)) {
+            return Some(ApiGatewayError::RouteDisabled {
+                name: switch.name.clone(),
+                message: switch.message.clone(),
+            });
+        }
+
+        let windows: Vec<&MaintenanceWindow> = self
+            .platform
+            .iter()
+            .chain(tenant_id.and_then(|tenant_id| self.tenants.get(tenant_id)))
+            .collect();
+        if let Some(window) = windows.iter().find(|window| window.phase(now) == MaintenancePhase::Active) {
+            return Some(ApiGatewayError::UnderMaintenance {
+                message: window.message.clone(),
+                tenant_id: window.tenant_id.clone(),
+                retry_after: window.retry_after(now),
+            });
+        }
+        if starts_workflow(router, method, path) {
+            if let Some(window) = windows.into_iter().min_by_key(|window| window.starts_at) {
+                return Some(ApiGatewayError::MaintenancePending {
+                    message: window.message.clone(),
+                    starts_at: window.starts_at,
+                });
+            }
+        }
+        None
+    }
+}
+
+/// Whether a request starts a workflow, rather than reading or signalling
+/// one that's already running
+fn starts_workflow(router: &IntelligentRouter, method: &Method, path: &str) -> bool {
+    if *method != Method::POST {
+        return false;
+    }
+    match router.classify_operation(method, path) {
+        Ok(OperationType::Workflow(_)) => match path.strip_prefix("/api/v1/workflows/") {
+            Some(rest) => !rest.trim_end_matches('/').contains('/'),
+            None => true,
+        },
+        _ => false,
+    }
+}
+
+pub struct MaintenanceControl {
+    redis_client: Arc<RedisClient>,
+    http_client: reqwest::Client,
+    router: Arc<IntelligentRouter>,
+    config: MaintenanceConfig,
+    snapshot: RwLock<Snapshot>,
+    branding: Mutex<HashMap<String, (Instant, Option<Branding>)>>,
+}
+
+impl MaintenanceControl {
+    pub fn new(
+        redis_url: &str,
+        config: MaintenanceConfig,
+        http_client: reqwest::Client,
+        router: Arc<IntelligentRouter>,
+    ) -> ApiResult<Self> {
+        let redis_client = RedisClient::open(redis_url).map_err(|e| ApiGatewayError::RedisError {
+            message: format!("Failed to create Redis client: {}", e),
+        })?;
+
+        Ok(Self {
+            redis_client: Arc::new(redis_client),
+            http_client,
+            router,
+            config,
+            snapshot: RwLock::new(Snapshot::default()),
+            branding: Mutex::new(HashMap::new()),
+        })
+    }
+
+    /// Refusal for a request, if maintenance or a kill switch applies to it
+    pub fn check(&self, method: &Method, path: &str, tenant_id: Option<&str>) -> ApiResult<()> {
+        let snapshot = self.snapshot.read().unwrap_or_else(|e| e.into_inner());
+        match snapshot.refusal(&self.router, method, path, tenant_id, Utc::now()) {
+            Some(refusal) => Err(refusal),
+            None => Ok(()),
+        }
+    }
+
+    /// Reads the windows and kill switches every instance shares
+    pub async fn reload(&self) -> ApiResult<()> {
+        let mut conn = self.redis_client.get_async_connection().await?;
+        let windows: HashMap<String, String> = conn.hgetall(WINDOWS_KEY).await?;
+        let switches: HashMap<String, String> = conn.hgetall(KILL_SWITCHES_KEY).await?;
+
+        let mut snapshot = Snapshot::default();
+        for (field, value) in windows {
+            let window: MaintenanceWindow = match serde_json::from_str(&value) {
+                Ok(window) => window,
+                Err(e) => {
+                    warn!(field = %field, error = %e, "Skipping unreadable maintenance window");
+                    continue;
+                }
+            };
+            match window.tenant_id.clone() {
+                Some(tenant_id) => {
+                    snapshot.tenants.insert(tenant_id, window);
+                }
+                None => snapshot.platform = Some(window),
+            }
+        }
+        for (name, value) in switches {
+            match serde_json::from_str(&value) {
+                Ok(switch) => snapshot.kill_switches.push(switch),
+                Err(e) => warn!(kill_switch = %name, error = %e, "Skipping unreadable kill switch"),
+            }
+        }
+        snapshot.kill_switches.sort_by(|a, b| a.name.cmp(&b.name));
+
+        *self.snapshot.write().unwrap_or_else(|e| e.into_inner()) = snapshot;
+        Ok(())
+    }
+
+    /// Keep the windows and switches in step with the other instances
+    pub fn spawn_refresh(self: Arc<Self>) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(Duration::from_secs(self.config.refresh_seconds.max(1)));
+            loop {
+                ticker.tick().await;
+                if let Err(e) = self.reload().await {
+                    warn!(error = %e, "Maintenance state refresh failed, keeping the last known state");
+                }
+            }
+        })
+    }
+
+    pub async fn enable(
+        &self,
+        tenant_id: Option<&str>,
+        request: EnableMaintenanceRequest,
+        enabled_by: Option<String>,
+    ) -> ApiResult<MaintenanceWindow> {
+        if request.drain_seconds > MAX_DRAIN_SECONDS {
+            return Err(ApiGatewayError::InvalidRequest {
+                message: format!("drain_seconds can be at most {}", MAX_DRAIN_SECONDS),
+            });
+        }
+        let now = Utc::now();
+        let starts_at = now + chrono::Duration::seconds(request.drain_seconds as i64);
+        if let Some(ends_at) = request.ends_at {
+            if ends_at <= starts_at {
+                return Err(ApiGatewayError::InvalidRequest {
+                    message: "ends_at must be after the window starts".to_string(),
+                });
+            }
+        }
+
+        let window = MaintenanceWindow {
+            tenant_id: tenant_id.map(str::to_string),
+            message: request
+                .message
+                .map(|message| message.trim().to_string())
+                .filter(|message| !message.is_empty())
+                .unwrap_or_else(|| DEFAULT_MESSAGE.to_string()),
+            starts_at,
+            ends_at: request.ends_at,
+            enabled_by,
+            enabled_at: now,
+        };
+        self.store(WINDOWS_KEY, &window_field(tenant_id), &window).await?;
+        info!(
+            tenant_id = ?window.tenant_id,
+            starts_at = %window.starts_at,
+            enabled_by = ?window.enabled_by,
+            "Maintenance window set"
+        );
+        self.reload().await?;
+        Ok(window)
+    }
+
+    /// Ends a window, reporting whether there was one
+    pub async fn disable(&self, tenant_id: Option<&str>) -> ApiResult<bool> {
+        let removed = self.remove(WINDOWS_KEY, &window_field(tenant_id)).await?;
+        if removed {
+            info!(tenant_id = ?tenant_id, "Maintenance window ended");
+        }
+        self.reload().await?;
+        Ok(removed)
+    }
+
+    pub async fn set_kill_switch(
+        &self,
+        name: &str,
+        request: KillSwitchRequest,
+        enabled_by: Option<String>,
+    ) -> ApiResult<KillSwitch> {
+        if !request.path.starts_with('/') {
+            return Err(ApiGatewayError::InvalidRequest {
+                message: format!("Kill switch path must start with '/': {}", request.path),
+            });
+        }
+        if let Some(method) = request.methods.iter().find(|m| Method::from_bytes(m.as_bytes()).is_err()) {
+            return Err(ApiGatewayError::InvalidRequest {
+                message: format!("Invalid HTTP method: {}", method),
+            });
+        }
+
+        let switch = KillSwitch {
+            name: name.to_string(),
+            path: request.path,
+            methods: request.methods.iter().map(|m| m.to_ascii_uppercase()).collect(),
+            message: request
+                .message
+                .map(|message| message.trim().to_string())
+                .filter(|message| !message.is_empty())
+                .unwrap_or_else(|| DEFAULT_KILL_SWITCH_MESSAGE.to_string()),
+            enabled_by,
+            enabled_at: Utc::now(),
+        };
+        self.store(KILL_SWITCHES_KEY, name, &switch).await?;
+        info!(kill_switch = %name, path = %switch.path, methods = ?switch.methods, "Kill switch on");
+        self.reload().await?;
+        Ok(switch)
+    }
+
+    /// Turns a kill switch off, reporting whether it was on
+    pub async fn remove_kill_switch(&self, name: &str) -> ApiResult<bool> {
+        let removed = self.remove(KILL_SWITCHES_KEY, name).await?;
+        if removed {
+            info!(kill_switch = %name, "Kill switch off");
+        }
+        self.reload().await?;
+        Ok(removed)
+    }
+
+    async fn store<T: Serialize>(&self, key: &str, field: &str, value: &T) -> ApiResult<()> {
+        let value = serde_json::to_string(value).map_err(|e| ApiGatewayError::InternalError {
+            message: format!("Failed to serialize maintenance state: {}", e),
+        })?;
+        let mut conn = self.redis_client.get_async_connection().await?;
+        conn.hset::<_, _, _, ()>(key, field, value).await?;
+        Ok(())
+    }
+
+    async fn remove(&self, key: &str, field: &str) -> ApiResult<bool> {
+        let mut conn = self.redis_client.get_async_connection().await?;
+        let removed: u64 = conn.hdel(key, field).await?;
+        Ok(removed > 0)
+    }
+
+    /// Current windows, with how many workflows each is still waiting on
+    pub async fn status(&self) -> MaintenanceStatus {
+        if let Err(e) = self.reload().await {
+            warn!(error = %e, "Maintenance state refresh failed, reporting the last known state");
+        }
+        let (platform, mut tenants, kill_switches) = {
+            let snapshot = self.snapshot.read().unwrap_or_else(|e| e.into_inner());
+            (
+                snapshot.platform.clone(),
+                snapshot.tenants.values().cloned().collect::<Vec<_>>(),
+                snapshot.kill_switches.clone(),
+            )
+        };
+        tenants.sort_by(|a, b| a.tenant_id.cmp(&b.tenant_id));
+
+        let now = Utc::now();
+        let mut statuses = Vec::new();
+        for window in platform.into_iter().chain(tenants) {
+            let in_flight_workflows = self.in_flight_workflows(window.tenant_id.as_deref()).await;
+            statuses.push(WindowStatus {
+                phase: window.phase(now),
+                window,
+                in_flight_workflows,
+            });
+        }
+        let platform = match statuses.first() {
+            Some(status) if status.window.tenant_id.is_none() => Some(statuses.remove(0)),
+            _ => None,
+        };
+
+        MaintenanceStatus {
+            platform,
+            tenants: statuses,
+            kill_switches,
+        }
+    }
+
+    /// Running workflows, across tenants unless one is given
+    async fn in_flight_workflows(&self, tenant_id: Option<&str>) -> Option<u64> {
+        let route = self.router.service_route("workflow").ok()?;
+        let mut request = self
+            .http_client
+            .get(format!("{}/api/v1/admin/workflows/in-flight", route.base_url))
+            .timeout(Duration::from_secs(route.timeout_seconds));
+        if let Some(tenant_id) = tenant_id {
+            request = request.query(&[("tenant_id", tenant_id)]);
+        }
+
+        let result: Result<Value, reqwest::Error> = async {
+            request.send().await?.error_for_status()?.json().await
+        }
+        .await;
+        match result {
+            Ok(in_flight) => in_flight.get("running").and_then(Value::as_u64),
+            Err(e) => {
+                warn!(tenant_id = ?tenant_id, error = %e, "Couldn't count in-flight workflows");
+                None
+            }
+        }
+    }
+
+    /// The 503 for a refused request, branded for the domain it was sent
+    /// to, as a page for browsers and JSON otherwise
+    pub async fn refusal_response(
+        &self,
+        refusal: ApiGatewayError,
+        request_id: &str,
+        host: Option<&str>,
+        wants_html: bool,
+    ) -> Response {
+        let branding = match host {
+            Some(host) => self.branding(host).await,
+            None => None,
+        };
+        let retry_after = match &refusal {
+            ApiGatewayError::UnderMaintenance { retry_after, .. } => *retry_after,
+            _ => None,
+        };
+
+        let mut response = if wants_html {
+            let page = maintenance_page(&refusal, branding.as_ref());
+            let mut response = Response::new(Body::from(page));
+            response.headers_mut().insert(
+                header::CONTENT_TYPE,
+                HeaderValue::from_static("text/html; charset=utf-8"),
+            );
+            response
+        } else {
+            let mut body = refusal.to_response(Some(request_id.to_string()));
+            if let Some(branding) = &branding {
+                let details = body.error.details.get_or_insert_with(|| serde_json::json!({}));
+                if let Some(details) = details.as_object_mut() {
+                    details.insert("branding".to_string(), serde_json::json!(branding));
+                }
+            }
+            Json(body).into_response()
+        };
+
+        *response.status_mut() = StatusCode::SERVICE_UNAVAILABLE;
+        response.headers_mut().insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+        if let Some(retry_after) = retry_after {
+            response.headers_mut().insert(header::RETRY_AFTER, HeaderValue::from(retry_after));
+        }
+        response
+    }
+
+    /// The published branding of a domain, cached; domains without any, or
+    /// white-label-service being down, get the platform's own page
+    async fn branding(&self, host: &str) -> Option<Branding> {
+        let domain = host.split(':').next().unwrap_or(host).trim_end_matches('.').to_ascii_lowercase();
+        let ttl = Duration::from_secs(self.config.branding_cache_seconds);
+        {
+            let cache = self.branding.lock().unwrap_or_else(|e| e.into_inner());
+            if let Some((fetched_at, branding)) = cache.get(&domain) {
+                if fetched_at.elapsed() < ttl {
+                    return branding.clone();
+                }
+            }
+        }
+
+        let branding = self.fetch_branding(&domain).await;
+        let mut cache = self.branding.lock().unwrap_or_else(|e| e.into_inner());
+        if cache.len() >= MAX_BRANDING_ENTRIES {
+            cache.retain(|_, (fetched_at, _)| fetched_at.elapsed() < ttl);
+            if cache.len() >= MAX_BRANDING_ENTRIES {
+                cache.clear();
+            }
+        }
+        cache.insert(domain, (Instant::now(), branding.clone()));
+        branding
+    }
+
+    async fn fetch_branding(&self, domain: &str) -> Option<Branding> {
+        let route = self.router.service_route("white_label").ok()?;
+        let response = self
+            .http_client
+            .get(format!("{}/api/v1/branding/{}", route.base_url, domain))
+            .timeout(Duration::from_secs(2))
+            .send()
+            .await
+            .ok()?;
+        if !response.status().is_success() {
+            debug!(domain = %domain, status = %response.status(), "No branding for maintenance page");
+            return None;
+        }
+        let published: Value = response.json().await.ok()?;
+        Some(Branding::from_published(&published))
+    }
+}
+
+fn window_field(tenant_id: Option<&str>) -> String {
+    match tenant_id {
+        Some(tenant_id) => format!("tenant:{}", tenant_id),
+        None => PLATFORM_FIELD.to_string(),
+    }
+}
+
+/// Hex colors only, since the color goes into the page's style
+fn is_css_color(color: &str) -> bool {
+    color
+        .strip_prefix('#')
+        .is_some_and(|hex| matches!(hex.len(), 3 | 6 | 8) && hex.chars().all(|c| c.is_ascii_hexdigit()))
+}
+
+fn escape_html(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn maintenance_page(refusal: &ApiGatewayError, branding: Option<&Branding>) -> String {
+    let brand_name = branding
+        .and_then(|branding| branding.brand_name.as_deref())
+        .unwrap_or("ADX Core");
+    let color = branding
+        .and_then(|branding| branding.primary_color.as_deref())
+        .unwrap_or("#2563eb");
+    let logo = branding
+        .and_then(|branding| branding.logo_url.as_deref())
+        .map(|url| format!("<img src=\"{}\" alt=\"{}\">", escape_html(url), escape_html(brand_name)))
+        .unwrap_or_default();
+    let title = match refusal {
+        ApiGatewayError::RouteDisabled { .. } => "Temporarily unavailable",
+        _ => "Down for maintenance",
+    };
+    let message = match refusal {
+        ApiGatewayError::UnderMaintenance { message, .. }
+        | ApiGatewayError::MaintenancePending { message, .. }
+        | ApiGatewayError::RouteDisabled { message, .. } => message.clone(),
+        other => other.to_string(),
+    };
+
+    format!(
+        "<!DOCTYPE html>\n<html><head><meta charset=\"utf-8\"><title>{brand} - {title}</title>\
+         <style>body{{font-family:sans-serif;text-align:center;padding:4rem 1rem;color:#1f2937}}\
+         h1{{color:{color}}}img{{max-height:4rem}}</style></head>\
+         <body>{logo}<h1>{title}</h1><p>{message}</p></body></html>\n",
+        brand = escape_html(brand_name),
+        title = title,
+        color = color,
+        logo = logo,
+        message = escape_html(&message),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn window(tenant_id: Option<&str>, starts_in_seconds: i64) -> MaintenanceWindow {
+        let now = Utc::now();
+        MaintenanceWindow {
+            tenant_id: tenant_id.map(str::to_string),
+            message: "Upgrading the database".to_string(),
+            starts_at: now + chrono::Duration::seconds(starts_in_seconds),
+            ends_at: Some(now + chrono::Duration::seconds(starts_in_seconds + 600)),
+            enabled_by: Some("ops".to_string()),
+            enabled_at: now,
+        }
+    }
+
+    fn refusal(snapshot: &Snapshot, method: Method, path: &str, tenant_id: Option<&str>) -> Option<ApiGatewayError> {
+        snapshot.refusal(&IntelligentRouter::new(), &method, path, tenant_id, Utc::now())
+    }
+
+    #[test]
+    fn test_tenant_window_only_refuses_its_tenant() {
+        let mut snapshot = Snapshot::default();
+        snapshot.tenants.insert("tenant-1".to_string(), window(Some("tenant-1"), -10));
+
+        assert!(matches!(
+            refusal(&snapshot, Method::GET, "/api/v1/users", Some("tenant-1")),
+            Some(ApiGatewayError::UnderMaintenance { retry_after: Some(_), .. })
+        ));
+        assert!(refusal(&snapshot, Method::GET, "/api/v1/users", Some("tenant-2")).is_none());
+        assert!(refusal(&snapshot, Method::GET, "/api/v1/users", None).is_none());
+    }
+
+    #[test]
+    fn test_draining_refuses_new_workflows_only() {
+        let snapshot = Snapshot {
+            platform: Some(window(None, 300)),
+            ..Snapshot::default()
+        };
+
+        assert!(matches!(
+            refusal(&snapshot, Method::POST, "/api/v1/tenants", Some("tenant-1")),
+            Some(ApiGatewayError::MaintenancePending { .. })
+        ));
+        assert!(matches!(
+            refusal(&snapshot, Method::POST, "/api/v1/workflows/user-onboarding", None),
+            Some(ApiGatewayError::MaintenancePending { .. })
+        ));
+        // Running workflows can still be followed and signalled
+        assert!(refusal(&snapshot, Method::POST, "/api/v1/workflows/wf-1/signal/approve", None).is_none());
+        assert!(refusal(&snapshot, Method::GET, "/api/v1/workflows/wf-1/status", None).is_none());
+        assert!(refusal(&snapshot, Method::GET, "/api/v1/users", None).is_none());
+    }
+
+    #[test]
+    fn test_kill_switch_matches_path_and_method() {
+        let snapshot = Snapshot {
+            kill_switches: vec![KillSwitch {
+                name: "exports".to_string(),
+                path: "/api/v1/files/*/export".to_string(),
+                methods: vec!["POST".to_string()],
+                message: DEFAULT_KILL_SWITCH_MESSAGE.to_string(),
+                enabled_by: None,
+                enabled_at: Utc::now(),
+            }],
+            ..Snapshot::default()
+        };
+
+        assert!(matches!(
+            refusal(&snapshot, Method::POST, "/api/v1/files/f-1/export", None),
+            Some(ApiGatewayError::RouteDisabled { name, .. }) if name == "exports"
+        ));
+        assert!(refusal(&snapshot, Method::GET, "/api/v1/files/f-1/export", None).is_none());
+        assert!(refusal(&snapshot, Method::POST, "/api/v1/files/f-1", None).is_none());
+    }
+
+    #[test]
+    fn test_branding_keeps_hex_colors_only() {
+        let published = serde_json::json!({
+            "brand_name": "Acme",
+            "logo_url": "/api/v1/branding/assets/logo",
+            "color_scheme": { "primary_color": "#ff6600" }
+        });
+        assert_eq!(
+            Branding::from_published(&published),
+            Branding {
+                brand_name: Some("Acme".to_string()),
+                logo_url: Some("/api/v1/branding/assets/logo".to_string()),
+                primary_color: Some("#ff6600".to_string()),
+            }
+        );
+
+        let published = serde_json::json!({ "color_scheme": { "primary_color": "red;}</style>" } });
+        assert_eq!(Branding::from_published(&published).primary_color, None);
+    }
+
+    #[test]
+    fn test_page_escapes_operator_text() {
+        let refusal = ApiGatewayError::UnderMaintenance {
+            message: "Back at <b>10:00</b>".to_string(),
+            tenant_id: None,
+            retry_after: None,
+        };
+        let branding = Branding {
+            brand_name: Some("Acme & Co".to_string()),
+            ..Branding::default()
+        };
+
+        let page = maintenance_page(&refusal, Some(&branding));
+        assert!(page.contains("Back at &lt;b&gt;10:00&lt;/b&gt;"));
+        assert!(page.contains("<title>Acme &amp; Co - Down for maintenance</title>"));
+    }
+}
@@ -1,25 +1,78 @@
 use axum::{
-    extract::{Request, State},
-    http::{HeaderMap, HeaderValue, StatusCode},
+    extract::{ConnectInfo, Request, State},
+    http::{header, HeaderMap, HeaderValue, Method, StatusCode},
     middleware::Next,
     response::{IntoResponse, Response},
 };
+use std::net::{IpAddr, SocketAddr};
 use std::sync::Arc;
 use tracing::{debug, warn, error, info};
 use uuid::Uuid;
 
 use adx_shared::{JwtClaims, TenantContext, UserContext};
+use adx_shared::audit::AuditEmitter;
+use adx_shared::context_token::{TenantContextClaims, TenantContextSigner, TENANT_CONTEXT_HEADER};
+use adx_shared::custom_domains::{CustomDomainClient, ACME_CHALLENGE_PREFIX};
+use adx_shared::entitlements::{AccessMode, EntitlementClient, Entitlements};
+use adx_shared::network_policy::NetworkPolicyClient;
+use adx_shared::signing_keys::SigningKeyClient;
 use crate::error::{ApiGatewayError, ApiResult};
-use crate::rate_limiter::{RateLimiter, check_rate_limit_middleware};
+use crate::handlers::GATEWAY_ADMIN_PERMISSION;
+use crate::idempotency::{self, Claim, IdempotencyStore, IDEMPOTENCY_KEY_HEADER};
+use crate::maintenance::MaintenanceControl;
+use crate::rate_limiter::RateLimiter;
+use crate::transform::TransformPolicies;
+use crate::validation::RequestValidator;
+use crate::webhooks::WEBHOOK_PATH_PREFIX;
+use crate::websocket;
 
 /// Shared state for middleware
 #[derive(Clone)]
 pub struct MiddlewareState {
     pub rate_limiter: Arc<RateLimiter>,
     pub jwt_secret: String,
+    /// Rotating JWT signing keys from security-service; None validates
+    /// against `jwt_secret` alone
+    pub signing_keys: Option<Arc<SigningKeyClient>>,
     pub require_auth: bool,
+    /// Verifies tenant context tokens issued by tenant-service on tenant switch
+    pub tenant_context_signer: Arc<TenantContextSigner>,
+    /// Entitlements whose access mode tenants are held to; None when
+    /// access mode enforcement is switched off
+    pub entitlement_client: Option<Arc<EntitlementClient>>,
+    /// Tenant network policies; None when enforcement is switched off
+    pub network_policy: Option<NetworkPolicyEnforcement>,
+    /// Tenants' verified custom domains; None when custom domains are off
+    pub custom_domains: Option<Arc<CustomDomainClient>>,
+    /// Stored responses for `Idempotency-Key` replays; None when off
+    pub idempotency: Option<Arc<IdempotencyStore>>,
+    /// Per-route header, path and field transformations; None when no
+    /// policies are configured
+    pub transforms: Option<Arc<TransformPolicies>>,
+    /// Request schemas from the combined OpenAPI document; None when
+    /// validation is switched off
+    pub validation: Option<Arc<RequestValidator>>,
+    /// Maintenance windows and route kill switches; None when switched off
+    pub maintenance: Option<Arc<MaintenanceControl>>,
 }
 
+/// What the network policy middleware needs besides the policies
+#[derive(Clone)]
+pub struct NetworkPolicyEnforcement {
+    pub client: Arc<NetworkPolicyClient>,
+    /// Refused requests are reported to security-service's audit log
+    pub audit: AuditEmitter,
+    pub country_header: String,
+    pub trust_forwarded_for: bool,
+    /// Proxies that append to X-Forwarded-For; see `client_ip`
+    pub trusted_proxies: usize,
+    /// Let requests through when the policy can't be fetched
+    pub fail_open: bool,
+}
+
+/// Response header telling clients the tenant is read-only
+pub const ACCESS_MODE_HEADER: &str = "X-Tenant-Access-Mode";
+
 /// Request context extracted from middleware
 #[derive(Debug, Clone)]
 pub struct RequestContext {
@@ -93,19 +146,22 @@ pub async fn auth_middleware(
         return next.run(request).await;
     }
 
-    // Extract authorization header
+    // Extract authorization header; WebSocket upgrades from browsers may
+    // carry the token in the query string instead
     let auth_header = request
         .headers()
         .get("Authorization")
-        .and_then(|h| h.to_str().ok());
+        .and_then(|h| h.to_str().ok())
+        .map(|h| h.to_string())
+        .or_else(|| websocket::query_token(request.uri()).map(|token| format!("Bearer {}", token)));
 
     let jwt_claims = if let Some(auth_header) = auth_header {
         // Extract and validate JWT token
-        let token = match extract_bearer_token(auth_header) {
+        let token = match extract_bearer_token(&auth_header) {
             Ok(token) => token,
             Err(e) => return e.into_response(),
         };
-        let claims = match validate_jwt_token(&token, &state.jwt_secret) {
+        let claims = match validate_request_token(&state, &token).await {
             Ok(claims) => claims,
             Err(e) => return e.into_response(),
         };
@@ -193,8 +249,14 @@ pub async fn rate_limiting_middleware(
     };
 
     // Check rate limits
-    if let Err(e) = check_rate_limit_middleware(&state.rate_limiter, tenant_id, user_id, path).await {
-        return e.into_response();
+    let result = match state.rate_limiter.check_rate_limit(tenant_id, user_id, path).await {
+        Ok(result) => result,
+        Err(e) => return e.into_response(),
+    };
+    if let Some(e) = result.to_error() {
+        let mut response = e.into_response();
+        result.apply_headers(response.headers_mut());
+        return response;
     }
 
     debug!(
@@ -204,23 +266,60 @@ pub async fn rate_limiting_middleware(
         "Rate limiting middleware passed"
     );
 
-    next.run(request).await
+    let mut response = next.run(request).await;
+    result.apply_headers(response.headers_mut());
+    response
 }
 
 /// Tenant context middleware - validates tenant access and injects tenant context
 pub async fn tenant_middleware(
-    request: Request,
+    State(state): State<MiddlewareState>,
+    mut request: Request,
     next: Next,
 ) -> Response {
-    let path = request.uri().path();
+    let path = request.uri().path().to_string();
     
     // Skip tenant validation for public endpoints
-    if is_public_endpoint(path) {
+    if is_public_endpoint(&path) {
         return next.run(request).await;
     }
 
     // Get request context
     let context = request.extensions().get::<RequestContext>().cloned();
+
+    // A signed context token is verified locally, with no tenant-service lookup
+    let context_token = request
+        .headers()
+        .get(TENANT_CONTEXT_HEADER)
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.to_string());
+
+    if let Some(context_token) = context_token {
+        let claims = match state.tenant_context_signer.verify(&context_token) {
+            Ok(claims) => claims,
+            Err(e) => return ApiGatewayError::InvalidToken { message: e.to_string() }.into_response(),
+        };
+
+        let mut context = context.unwrap_or_else(RequestContext::new);
+        if let Some(jwt_claims) = &context.jwt_claims {
+            if jwt_claims.sub != claims.sub {
+                return ApiGatewayError::TenantAccessDenied {
+                    reason: "Tenant context token was issued to a different user".to_string(),
+                }.into_response();
+            }
+        }
+
+        debug!(
+            path = %path,
+            tenant_id = %claims.tenant_id,
+            session_id = %claims.session_id,
+            "Tenant context token verified"
+        );
+
+        context.tenant_context = Some(tenant_context_from_claims(&claims));
+        request.extensions_mut().insert(context);
+        return next.run(request).await;
+    }
     
     if let Some(context) = context {
         if let Some(tenant_context) = &context.tenant_context {
@@ -237,7 +336,7 @@ pub async fn tenant_middleware(
             }
 
             debug!(
-                path = path,
+                path = %path,
                 tenant_id = %tenant_context.tenant_id,
                 tenant_name = %tenant_context.tenant_name,
                 "Tenant middleware validated"
@@ -248,6 +347,399 @@ pub async fn tenant_middleware(
     next.run(request).await
 }
 
+/// Custom domain middleware - pins requests on a tenant's custom domain to
+/// that tenant. A token for any other tenant is refused, and requests
+/// without one, like sign-in, are sent on with the domain's tenant.
+pub async fn custom_domain_middleware(
+    State(state): State<MiddlewareState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(custom_domains) = state.custom_domains.clone() else {
+        return next.run(request).await;
+    };
+
+    let Some(host) = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.to_string())
+    else {
+        return next.run(request).await;
+    };
+
+    let domain_tenant = match custom_domains.tenant_for_host(&host).await {
+        Ok(Some(tenant_id)) => tenant_id,
+        Ok(None) => return next.run(request).await,
+        Err(e) => {
+            // Requests still need a valid token, which names their tenant
+            warn!(host = %host, error = %e, "Custom domain lookup failed, not pinning tenant");
+            return next.run(request).await;
+        }
+    };
+
+    let request_tenant = request.extensions()
+        .get::<RequestContext>()
+        .and_then(|context| context.tenant_context.as_ref())
+        .map(|tenant| tenant.tenant_id.clone());
+
+    match request_tenant {
+        Some(tenant_id) if tenant_id != domain_tenant => {
+            debug!(host = %host, tenant_id = %tenant_id, domain_tenant = %domain_tenant, "Request for another tenant on custom domain");
+            return ApiGatewayError::TenantAccessDenied {
+                reason: "This domain belongs to a different tenant".to_string(),
+            }.into_response();
+        }
+        Some(_) => {}
+        None => match HeaderValue::from_str(&domain_tenant) {
+            // Replaces any tenant header the client sent
+            Ok(value) => {
+                request.headers_mut().insert("X-Tenant-ID", value);
+            }
+            Err(_) => warn!(host = %host, "Custom domain tenant id is not a valid header value"),
+        },
+    }
+
+    next.run(request).await
+}
+
+/// Maintenance middleware - refuses requests during a maintenance window
+/// and on routes that are switched off, and new workflows while a window is
+/// draining. Gateway admins pass, so they can check and lift it.
+pub async fn maintenance_middleware(
+    State(state): State<MiddlewareState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(maintenance) = state.maintenance.clone() else {
+        return next.run(request).await;
+    };
+
+    let path = request.uri().path().to_string();
+    if is_maintenance_exempt(&path) {
+        return next.run(request).await;
+    }
+
+    let context = request.extensions().get::<RequestContext>().cloned();
+    let is_admin = context
+        .as_ref()
+        .and_then(|context| context.user_context.as_ref())
+        .is_some_and(|user| {
+            user.permissions
+                .iter()
+                .any(|p| p == GATEWAY_ADMIN_PERMISSION || p == "*")
+        });
+    if is_admin {
+        return next.run(request).await;
+    }
+
+    let tenant_id = context
+        .as_ref()
+        .and_then(|context| context.tenant_context.as_ref())
+        .map(|tenant| tenant.tenant_id.clone());
+    let Err(refusal) = maintenance.check(request.method(), &path, tenant_id.as_deref()) else {
+        return next.run(request).await;
+    };
+
+    debug!(path = %path, tenant_id = ?tenant_id, error = %refusal, "Request refused for maintenance");
+    let request_id = context
+        .map(|context| context.request_id)
+        .unwrap_or_else(|| Uuid::new_v4().to_string());
+    let host = request
+        .headers()
+        .get(header::HOST)
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.to_string());
+    let wants_html = request
+        .headers()
+        .get(header::ACCEPT)
+        .and_then(|h| h.to_str().ok())
+        .is_some_and(|accept| accept.contains("text/html"));
+    maintenance.refusal_response(refusal, &request_id, host.as_deref(), wants_html).await
+}
+
+/// Paths that keep working during maintenance: health checks, the
+/// gateway's own endpoints, certificate challenges, the theme the
+/// maintenance page is drawn with, and sign-in, so operators can get a
+/// token to lift it
+fn is_maintenance_exempt(path: &str) -> bool {
+    matches!(path,
+        "/health" |
+        "/metrics" |
+        "/api/v1/health" |
+        "/api/v1/auth/login" |
+        "/api/v1/auth/refresh"
+    ) || path.starts_with("/gateway/")
+        || path.starts_with(ACME_CHALLENGE_PREFIX)
+        || is_public_branding(path)
+}
+
+/// Access mode middleware - holds tenants with a lapsed license to read-only
+/// access during their grace period and blocks them after it
+pub async fn access_mode_middleware(
+    State(state): State<MiddlewareState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(entitlement_client) = state.entitlement_client.clone() else {
+        return next.run(request).await;
+    };
+
+    let path = request.uri().path().to_string();
+    if is_public_endpoint(&path) {
+        return next.run(request).await;
+    }
+
+    let tenant_id = request.extensions()
+        .get::<RequestContext>()
+        .and_then(|context| context.tenant_context.as_ref())
+        .map(|tenant| tenant.tenant_id.clone());
+    let Some(tenant_id) = tenant_id else {
+        return next.run(request).await;
+    };
+
+    // License-service being unreachable shouldn't take every tenant down with it
+    let entitlements = match entitlement_client.entitlements(&tenant_id).await {
+        Ok(entitlements) => entitlements,
+        Err(e) => {
+            warn!(tenant_id = %tenant_id, error = %e, "Entitlement lookup failed, not enforcing access mode");
+            return next.run(request).await;
+        }
+    };
+
+    if let Err(e) = check_access_mode(&entitlements, request.method(), &path) {
+        debug!(path = %path, tenant_id = %tenant_id, "Request blocked by tenant access mode");
+        return e.into_response();
+    }
+
+    let mut response = next.run(request).await;
+    if entitlements.access_mode != AccessMode::Full {
+        response.headers_mut().insert(
+            ACCESS_MODE_HEADER,
+            HeaderValue::from_static(access_mode_name(entitlements.access_mode)),
+        );
+    }
+    response
+}
+
+/// Idempotency middleware - replays the stored response for a mutating
+/// request repeated with the same `Idempotency-Key`, instead of running it
+/// again
+pub async fn idempotency_middleware(
+    State(state): State<MiddlewareState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(store) = state.idempotency.clone() else {
+        return next.run(request).await;
+    };
+    if !idempotency::applies_to(request.method()) {
+        return next.run(request).await;
+    }
+    let Some(key) = request.headers().get(IDEMPOTENCY_KEY_HEADER) else {
+        return next.run(request).await;
+    };
+    let key = match idempotency::validate_key(key) {
+        Ok(key) => key,
+        Err(e) => return e.into_response(),
+    };
+
+    let context = request.extensions().get::<RequestContext>();
+    let tenant_id = context
+        .and_then(|context| context.tenant_context.as_ref())
+        .map(|t| t.tenant_id.clone())
+        .unwrap_or_else(|| "anonymous".to_string());
+    let user_id = context
+        .and_then(|context| context.user_context.as_ref())
+        .map(|u| u.user_id.clone())
+        .unwrap_or_else(|| "anonymous".to_string());
+    let scoped_key = IdempotencyStore::scoped_key(&tenant_id, &user_id, &key);
+
+    // The body is part of what makes a repeat the same request
+    let (parts, body) = request.into_parts();
+    let body = match axum::body::to_bytes(body, usize::MAX).await {
+        Ok(body) => body,
+        Err(e) => {
+            return ApiGatewayError::InvalidRequest {
+                message: format!("Failed to read request body: {}", e),
+            }
+            .into_response()
+        }
+    };
+    let fingerprint = idempotency::fingerprint(&parts.method, parts.uri.path(), &body);
+    let request = Request::from_parts(parts, axum::body::Body::from(body));
+
+    match store.claim(&scoped_key, &fingerprint).await {
+        Ok(Claim::Acquired) => {}
+        Ok(Claim::Replay(stored)) => {
+            debug!(tenant_id = %tenant_id, "Replaying stored response for idempotency key");
+            return stored.into_response();
+        }
+        // Redis being unreachable shouldn't stop mutating requests altogether
+        Err(ApiGatewayError::RedisError { message }) => {
+            warn!(error = %message, "Idempotency store unavailable, not deduplicating request");
+            return next.run(request).await;
+        }
+        Err(e) => return e.into_response(),
+    }
+
+    let response = next.run(request).await;
+    store.complete(&scoped_key, &fingerprint, response).await
+}
+
+/// Transformation middleware - applies the route's transformation policy
+/// to the request before it's routed and to the response on the way back
+pub async fn transform_middleware(
+    State(state): State<MiddlewareState>,
+    mut request: Request,
+    next: Next,
+) -> Response {
+    let Some(transforms) = state.transforms.clone() else {
+        return next.run(request).await;
+    };
+    let Some(policy) = transforms.policy_for(request.method(), request.uri().path()) else {
+        return next.run(request).await;
+    };
+
+    let context = request.extensions().get::<RequestContext>().cloned()
+        .unwrap_or_else(RequestContext::new);
+    if let Err(e) = policy.request.apply(&mut request, &context) {
+        return e.into_response();
+    }
+
+    let response = next.run(request).await;
+    policy.response.apply(response, &context).await
+}
+
+/// Validation middleware - refuses requests whose query parameters or JSON
+/// body don't match their operation's schema, listing every offending field
+pub async fn validation_middleware(
+    State(state): State<MiddlewareState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(validator) = state.validation.clone() else {
+        return next.run(request).await;
+    };
+    let schemas = validator.schemas().await;
+    let Some(operation) = schemas.find(request.method(), request.uri().path()) else {
+        return next.run(request).await;
+    };
+
+    let mut errors = operation.validate_query(request.uri(), validator.max_errors());
+
+    // Bodies in other formats are left to the service to refuse
+    let content_type = request.headers().get(header::CONTENT_TYPE).and_then(|v| v.to_str().ok());
+    let is_json = match content_type {
+        Some(content_type) => content_type.starts_with("application/json") || content_type.contains("+json"),
+        None => true,
+    };
+    let request = if operation.has_body_schema() && is_json {
+        let (parts, body) = request.into_parts();
+        let body = match axum::body::to_bytes(body, usize::MAX).await {
+            Ok(body) => body,
+            Err(e) => {
+                return ApiGatewayError::InvalidRequest {
+                    message: format!("Failed to read request body: {}", e),
+                }
+                .into_response()
+            }
+        };
+        errors.extend(operation.validate_body(&body, validator.max_errors()));
+        Request::from_parts(parts, axum::body::Body::from(body))
+    } else {
+        request
+    };
+
+    if !errors.is_empty() {
+        errors.truncate(validator.max_errors());
+        debug!(
+            method = %request.method(),
+            path = %request.uri().path(),
+            errors = errors.len(),
+            "Refusing request that doesn't match its schema"
+        );
+        return ApiGatewayError::SchemaViolation { errors }.into_response();
+    }
+
+    next.run(request).await
+}
+
+/// Network policy middleware - refuses requests from addresses and countries
+/// the tenant's network policy does not allow, and audits each refusal
+pub async fn network_policy_middleware(
+    State(state): State<MiddlewareState>,
+    request: Request,
+    next: Next,
+) -> Response {
+    let Some(enforcement) = state.network_policy.clone() else {
+        return next.run(request).await;
+    };
+
+    let path = request.uri().path().to_string();
+    if is_public_endpoint(&path) {
+        return next.run(request).await;
+    }
+
+    let context = request.extensions().get::<RequestContext>().cloned();
+    let Some(tenant_id) = context
+        .as_ref()
+        .and_then(|context| context.tenant_context.as_ref())
+        .map(|tenant| tenant.tenant_id.clone())
+    else {
+        return next.run(request).await;
+    };
+
+    let trusted_proxies = enforcement.trust_forwarded_for.then_some(enforcement.trusted_proxies);
+    let ip = client_ip(&request, trusted_proxies);
+    let country = request
+        .headers()
+        .get(enforcement.country_header.as_str())
+        .and_then(|h| h.to_str().ok())
+        .map(|s| s.to_string());
+
+    // While security-service is unreachable and no policy is cached,
+    // requests are refused unless the gateway is set to fail open
+    let denial = match enforcement.client.check(&tenant_id, ip, country.as_deref()).await {
+        Ok(result) => result.err(),
+        Err(e) if enforcement.fail_open => {
+            warn!(tenant_id = %tenant_id, error = %e, "Network policy lookup failed, not enforcing it");
+            None
+        }
+        Err(e) => {
+            error!(tenant_id = %tenant_id, error = %e, "Network policy lookup failed, refusing request");
+            return ApiGatewayError::ServiceUnavailable {
+                service: "security-service".to_string(),
+            }
+            .into_response();
+        }
+    };
+
+    if let Some(reason) = denial {
+        warn!(
+            path = %path,
+            tenant_id = %tenant_id,
+            ip = ?ip,
+            country = ?country,
+            reason = reason.as_str(),
+            "Request refused by tenant network policy"
+        );
+
+        let mut event = reason.audit_event(&tenant_id, "api-gateway", ip, country.as_deref(), &path);
+        if let Some(context) = &context {
+            event = event.request_id(context.request_id.clone());
+            if let Some(user) = &context.user_context {
+                event = event.actor(user.user_id.clone());
+            }
+        }
+        enforcement.audit.emit(event);
+
+        return ApiGatewayError::NetworkAccessDenied { reason }.into_response();
+    }
+
+    next.run(request).await
+}
+
 /// CORS middleware
 pub async fn cors_middleware(
     request: Request,
@@ -267,11 +759,11 @@ pub async fn cors_middleware(
     );
     headers.insert(
         "Access-Control-Allow-Headers",
-        HeaderValue::from_static("Content-Type, Authorization, X-Tenant-ID, X-Request-ID"),
+        HeaderValue::from_static("Content-Type, Authorization, X-Tenant-ID, X-Tenant-Context, X-Request-ID, Idempotency-Key"),
     );
     headers.insert(
         "Access-Control-Expose-Headers",
-        HeaderValue::from_static("X-Request-ID, X-Rate-Limit-Remaining"),
+        HeaderValue::from_static("X-Request-ID, X-Rate-Limit-Remaining, X-Tenant-Access-Mode, Idempotent-Replayed, RateLimit-Limit, RateLimit-Remaining, RateLimit-Reset, RateLimit-Policy, Retry-After"),
     );
 
     response
@@ -321,10 +813,96 @@ fn is_public_endpoint(path: &str) -> bool {
         "/health" | 
         "/metrics" | 
         "/api/v1/health" |
+        "/gateway/health/upstreams" |
         "/api/v1/auth/login" |
         "/api/v1/auth/register" |
         "/api/v1/auth/refresh"
-    )
+    ) || is_module_asset(path)
+        || is_public_branding(path)
+        || is_hosted_auth_page(path)
+        || path.starts_with(ACME_CHALLENGE_PREFIX)
+        || path.starts_with(WEBHOOK_PATH_PREFIX)
+}
+
+/// Module frontend assets are versioned bundles the browser loads with
+/// script tags, which carry no bearer token
+fn is_module_asset(path: &str) -> bool {
+    path.starts_with("/api/v1/modules/assets/")
+}
+
+/// The shell loads its domain's theme before anyone signs in
+fn is_public_branding(path: &str) -> bool {
+    path.starts_with("/api/v1/branding/")
+}
+
+/// auth-service's hosted sign-in and registration pages
+fn is_hosted_auth_page(path: &str) -> bool {
+    path.starts_with("/api/v1/auth/hosted/")
+}
+
+/// Billing stays reachable for tenants with a lapsed license, so they can
+/// pay, and so does signing out
+fn is_billing_endpoint(path: &str) -> bool {
+    ["/api/v1/billing", "/api/v1/licenses", "/api/v1/entitlements", "/api/v1/auth/"]
+        .iter()
+        .any(|prefix| path.starts_with(prefix))
+}
+
+/// GraphQL queries are POSTed, but the schema has no mutations
+fn is_query_endpoint(path: &str) -> bool {
+    path == "/graphql"
+}
+
+fn check_access_mode(entitlements: &Entitlements, method: &Method, path: &str) -> ApiResult<()> {
+    if entitlements.access_mode.allows_method(method.as_str()) || is_billing_endpoint(path) {
+        return Ok(());
+    }
+    if entitlements.is_read_only() && is_query_endpoint(path) {
+        return Ok(());
+    }
+
+    match entitlements.access_mode {
+        AccessMode::ReadOnly => Err(ApiGatewayError::TenantReadOnly {
+            tenant_id: entitlements.tenant_id.clone(),
+            grace_ends_at: entitlements.grace_ends_at,
+        }),
+        _ => Err(ApiGatewayError::TenantSuspended {
+            tenant_id: entitlements.tenant_id.clone(),
+        }),
+    }
+}
+
+fn access_mode_name(access_mode: AccessMode) -> &'static str {
+    match access_mode {
+        AccessMode::Full => "full",
+        AccessMode::ReadOnly => "read_only",
+        AccessMode::Suspended => "suspended",
+    }
+}
+
+/// The client's address. Behind `trusted_proxies` proxies that each append
+/// the address they saw to X-Forwarded-For, it is the entry that many from
+/// the right; entries left of it came from the client and can't be trusted.
+/// The peer address otherwise.
+fn client_ip(request: &Request, trusted_proxies: Option<usize>) -> Option<IpAddr> {
+    if let Some(trusted_proxies) = trusted_proxies.filter(|hops| *hops > 0) {
+        let forwarded = request
+            .headers()
+            .get_all("X-Forwarded-For")
+            .iter()
+            .filter_map(|h| h.to_str().ok())
+            .flat_map(|value| value.split(','))
+            .map(str::trim)
+            .collect::<Vec<_>>();
+        if let Some(hop) = forwarded.len().checked_sub(trusted_proxies).map(|index| forwarded[index]) {
+            return hop.parse().ok();
+        }
+    }
+
+    request
+        .extensions()
+        .get::<ConnectInfo<SocketAddr>>()
+        .map(|ConnectInfo(address)| address.ip())
 }
 
 fn is_health_endpoint(path: &str) -> bool {
@@ -341,27 +919,87 @@ fn extract_bearer_token(auth_header: &str) -> ApiResult<String> {
     }
 }
 
-fn validate_jwt_token(token: &str, secret: &str) -> ApiResult<JwtClaims> {
+/// Check a token against the key its `kid` names in the rotating key set.
+/// Tokens without a key id predate rotation and are checked against every
+/// valid key and the static secret.
+async fn validate_request_token(state: &MiddlewareState, token: &str) -> ApiResult<JwtClaims> {
+    let Some(signing_keys) = state.signing_keys.as_ref() else {
+        return validate_jwt_token(token, &[state.jwt_secret.as_str()]);
+    };
+
+    let kid = jsonwebtoken::decode_header(token)
+        .map_err(|e| ApiGatewayError::InvalidToken {
+            message: format!("Invalid JWT token: {}", e),
+        })?
+        .kid;
+
+    let key_set = match signing_keys.jwt_key_set(kid.as_deref()).await {
+        Ok(key_set) => Some(key_set),
+        Err(e) => {
+            warn!(error = %e, "Signing keys unavailable");
+            None
+        }
+    };
+
+    let mut secrets = key_set
+        .as_ref()
+        .map(|key_set| key_set.verification_secrets(kid.as_deref(), chrono::Utc::now()))
+        .unwrap_or_default();
+    if kid.is_none() {
+        secrets.push(state.jwt_secret.as_str());
+    }
+    if secrets.is_empty() {
+        return Err(ApiGatewayError::InvalidToken {
+            message: "Token is signed with an unknown or retired key".to_string(),
+        });
+    }
+
+    validate_jwt_token(token, &secrets)
+}
+
+fn validate_jwt_token(token: &str, secrets: &[&str]) -> ApiResult<JwtClaims> {
     use jsonwebtoken::{decode, DecodingKey, Validation, Algorithm};
 
-    let key = DecodingKey::from_secret(secret.as_ref());
     let validation = Validation::new(Algorithm::HS256);
+    let mut last_error = None;
 
-    match decode::<JwtClaims>(token, &key, &validation) {
-        Ok(token_data) => {
-            // Check if token is expired
-            let now = chrono::Utc::now().timestamp();
-            if token_data.claims.exp < now {
-                return Err(ApiGatewayError::InvalidToken {
-                    message: "Token has expired".to_string(),
-                });
-            }
+    for secret in secrets {
+        let key = DecodingKey::from_secret(secret.as_ref());
+        match decode::<JwtClaims>(token, &key, &validation) {
+            Ok(token_data) => {
+                // Check if token is expired
+                let now = chrono::Utc::now().timestamp();
+                if token_data.claims.exp < now {
+                    return Err(ApiGatewayError::InvalidToken {
+                        message: "Token has expired".to_string(),
+                    });
+                }
 
-            Ok(token_data.claims)
+                return Ok(token_data.claims);
+            }
+            Err(e) => last_error = Some(e),
         }
-        Err(e) => Err(ApiGatewayError::InvalidToken {
-            message: format!("Invalid JWT token: {}", e),
-        }),
+    }
+
+    Err(ApiGatewayError::InvalidToken {
+        message: match last_error {
+            Some(e) => format!("Invalid JWT token: {}", e),
+            None => "No key to validate the token with".to_string(),
+        },
+    })
+}
+
+fn tenant_context_from_claims(claims: &TenantContextClaims) -> TenantContext {
+    TenantContext {
+        tenant_id: claims.tenant_id.clone(),
+        tenant_name: claims.tenant_name.clone(),
+        subscription_tier: claims.subscription_tier.clone(),
+        features: claims.features.clone(),
+        quotas: adx_shared::TenantQuotas::default(), // Quotas are enforced by the owning services
+        settings: Default::default(),
+        is_active: true,
+        created_at: chrono::Utc::now(),
+        updated_at: chrono::Utc::now(),
     }
 }
 
@@ -383,6 +1021,62 @@ mod tests {
         assert!(is_public_endpoint("/api/v1/auth/login"));
         assert!(!is_public_endpoint("/api/v1/users"));
         assert!(!is_public_endpoint("/api/v1/workflows/test"));
+        assert!(is_public_endpoint("/api/v1/modules/assets/crm/1.0.0/remoteEntry.js"));
+        assert!(!is_public_endpoint("/api/v1/modules/frontend-manifest/tenant-1"));
+        assert!(is_public_endpoint("/.well-known/acme-challenge/LoqXcYV8q5ONbJQxbmR7SCTNo3tiAXDfowyjxAjEuX0"));
+        assert!(!is_public_endpoint("/api/v1/white-label/domains"));
+        assert!(is_public_endpoint("/api/v1/branding/app.acme.com"));
+        assert!(!is_public_endpoint("/api/v1/white-label/branding/draft"));
+        assert!(is_public_endpoint("/api/v1/branding/app.acme.com/login-page"));
+        assert!(is_public_endpoint("/api/v1/branding/app.acme.com/translations/fr-ca/common"));
+        assert!(!is_public_endpoint("/api/v1/white-label/translations/export"));
+        assert!(is_public_endpoint("/api/v1/auth/hosted/login"));
+        assert!(!is_public_endpoint("/api/v1/white-label/login-page/draft"));
+        assert!(is_public_endpoint("/webhooks/stripe"));
+        assert!(!is_public_endpoint("/gateway/webhooks/stripe/deliveries"));
+    }
+
+    #[test]
+    fn test_maintenance_exempt_paths() {
+        assert!(is_maintenance_exempt("/health"));
+        assert!(is_maintenance_exempt("/gateway/maintenance"));
+        assert!(is_maintenance_exempt("/api/v1/auth/login"));
+        assert!(is_maintenance_exempt("/api/v1/branding/app.acme.com"));
+        assert!(!is_maintenance_exempt("/api/v1/auth/register"));
+        assert!(!is_maintenance_exempt("/api/v1/users"));
+        assert!(!is_maintenance_exempt("/webhooks/stripe"));
+    }
+
+    #[test]
+    fn test_client_ip_ignores_spoofed_forwarded_for_entries() {
+        let peer: SocketAddr = "10.0.0.5:443".parse().unwrap();
+        let request = |forwarded_for: &str| {
+            let mut request = Request::builder()
+                .uri("/api/v1/users")
+                .header("X-Forwarded-For", forwarded_for)
+                .body(axum::body::Body::empty())
+                .unwrap();
+            request.extensions_mut().insert(ConnectInfo(peer));
+            request
+        };
+
+        // The client claims an allowlisted address; the proxy appends the real one
+        let spoofed = request("198.51.100.10, 203.0.113.7");
+        assert_eq!(client_ip(&spoofed, Some(1)), "203.0.113.7".parse().ok());
+        assert_eq!(client_ip(&spoofed, Some(2)), "198.51.100.10".parse().ok());
+
+        // Fewer entries than trusted proxies: the header can't be relied on
+        assert_eq!(client_ip(&request("203.0.113.7"), Some(2)), Some(peer.ip()));
+
+        // Not trusting the header at all
+        assert_eq!(client_ip(&spoofed, None), Some(peer.ip()));
+
+        // No peer address to fall back on
+        let bare = Request::builder()
+            .uri("/api/v1/users")
+            .body(axum::body::Body::empty())
+            .unwrap();
+        assert_eq!(client_ip(&bare, Some(1)), None);
     }
 
     #[test]
@@ -397,6 +1091,53 @@ mod tests {
         assert!(result.is_err());
     }
 
+    fn entitlements(access_mode: AccessMode) -> Entitlements {
+        Entitlements {
+            tenant_id: "tenant-1".to_string(),
+            subscription_tier: Some("Professional".to_string()),
+            license_status: Some("Suspended".to_string()),
+            active: false,
+            features: vec![],
+            restricted_features: vec![],
+            quotas: vec![],
+            modules: vec![],
+            access_mode,
+            grace_ends_at: None,
+            generated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_read_only_tenant_can_only_read() {
+        let read_only = entitlements(AccessMode::ReadOnly);
+
+        assert!(check_access_mode(&read_only, &Method::GET, "/api/v1/users").is_ok());
+        assert!(check_access_mode(&read_only, &Method::OPTIONS, "/api/v1/users").is_ok());
+        assert!(matches!(
+            check_access_mode(&read_only, &Method::POST, "/api/v1/users"),
+            Err(ApiGatewayError::TenantReadOnly { .. })
+        ));
+        assert!(check_access_mode(&read_only, &Method::POST, "/api/v1/billing/checkout").is_ok());
+        assert!(check_access_mode(&read_only, &Method::POST, "/graphql").is_ok());
+        assert!(check_access_mode(&entitlements(AccessMode::Full), &Method::DELETE, "/api/v1/users/1").is_ok());
+    }
+
+    #[test]
+    fn test_suspended_tenant_can_only_reach_billing() {
+        let suspended = entitlements(AccessMode::Suspended);
+
+        assert!(matches!(
+            check_access_mode(&suspended, &Method::GET, "/api/v1/files"),
+            Err(ApiGatewayError::TenantSuspended { .. })
+        ));
+        assert!(check_access_mode(&suspended, &Method::GET, "/api/v1/entitlements/tenant-1").is_ok());
+        assert!(check_access_mode(&suspended, &Method::POST, "/api/v1/auth/logout").is_ok());
+        assert!(matches!(
+            check_access_mode(&suspended, &Method::POST, "/graphql"),
+            Err(ApiGatewayError::TenantSuspended { .. })
+        ));
+    }
+
     #[test]
     fn test_health_endpoint_detection() {
         assert!(is_health_endpoint("/health"));
@@ -0,0 +1,470 @@
+//! Shadow traffic for validating new backend versions.
+//!
+//! A service with a mirror target has a sample of its direct requests sent
+//! a second time, to the target, after the client has been answered from
+//! the service itself. The target's status and JSON body are compared with
+//! the service's, and mismatches are kept per service for the admin
+//! endpoint. Clients never wait on, or see anything of, the shadow call:
+//! it runs in the background, within its own concurrency limit and timeout,
+//! and is skipped rather than queued when the limit is reached.
+
+use axum::body::Bytes;
+use chrono::{DateTime, Utc};
+use serde::Serialize;
+use serde_json::Value;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, VecDeque};
+use std::hash::{Hash, Hasher};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::Semaphore;
+use tracing::{debug, warn};
+
+use crate::config::{MirrorConfig, MirrorTarget};
+use crate::error::{ApiGatewayError, ApiResult};
+
+/// Set on mirrored requests, so the target can skip side effects such as
+/// sending email
+pub const SHADOW_HEADER: &str = "X-Shadow-Request";
+
+/// Most differing fields recorded for one response
+const MAX_DIFFERENCES: usize = 20;
+
+/// A direct request as it was sent to the service
+#[derive(Debug, Clone)]
+pub struct ShadowRequest {
+    pub request_id: String,
+    pub method: reqwest::Method,
+    /// Path and query, as appended to the service's base URL
+    pub path: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Bytes,
+}
+
+/// What the client was answered with
+#[derive(Debug, Clone)]
+pub struct PrimaryResponse {
+    pub status: u16,
+    pub body: Bytes,
+    pub latency: Duration,
+}
+
+/// A mirrored request whose responses didn't match
+#[derive(Debug, Clone, Serialize)]
+pub struct MirrorDiff {
+    pub request_id: String,
+    pub method: String,
+    /// Without the query, which may carry credentials
+    pub path: String,
+    pub primary_status: u16,
+    /// None when the target couldn't be reached
+    pub shadow_status: Option<u16>,
+    /// Dotted paths of JSON fields that differ; values aren't kept
+    pub differences: Vec<String>,
+    pub primary_latency_ms: u64,
+    pub shadow_latency_ms: u64,
+    pub error: Option<String>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+/// Counters and recent mismatches of one mirrored service
+#[derive(Debug, Clone, Serialize)]
+pub struct MirrorStatus {
+    pub service: String,
+    pub target: String,
+    pub sample_percent: f64,
+    pub mirrored: u64,
+    pub matched: u64,
+    pub mismatched: u64,
+    pub failed: u64,
+    /// Sampled requests dropped because too many shadow calls were in flight
+    pub skipped: u64,
+    pub recent_diffs: Vec<MirrorDiff>,
+}
+
+struct MirroredService {
+    target: MirrorTarget,
+    mirrored: AtomicU64,
+    matched: AtomicU64,
+    mismatched: AtomicU64,
+    failed: AtomicU64,
+    skipped: AtomicU64,
+    recent: Mutex<VecDeque<MirrorDiff>>,
+}
+
+pub struct TrafficMirror {
+    client: reqwest::Client,
+    services: HashMap<String, Arc<MirroredService>>,
+    slots: Arc<Semaphore>,
+    max_diffs: usize,
+}
+
+impl TrafficMirror {
+    pub fn new(config: &MirrorConfig) -> ApiResult<Self> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(config.timeout_seconds))
+            .build()
+            .map_err(|e| ApiGatewayError::ConfigurationError {
+                message: format!("Failed to create mirror HTTP client: {}", e),
+            })?;
+
+        let services = config
+            .targets
+            .iter()
+            .map(|(service, target)| {
+                let mirrored = MirroredService {
+                    target: target.clone(),
+                    mirrored: AtomicU64::new(0),
+                    matched: AtomicU64::new(0),
+                    mismatched: AtomicU64::new(0),
+                    failed: AtomicU64::new(0),
+                    skipped: AtomicU64::new(0),
+                    recent: Mutex::new(VecDeque::new()),
+                };
+                (service.clone(), Arc::new(mirrored))
+            })
+            .collect();
+
+        Ok(Self {
+            client,
+            services,
+            slots: Arc::new(Semaphore::new(config.max_concurrent.max(1))),
+            max_diffs: config.max_diffs,
+        })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.services.is_empty()
+    }
+
+    /// Whether a request to the service is in the mirrored sample. The
+    /// decision follows the request id, so it's the same wherever it's made.
+    pub fn should_mirror(&self, service: &str, method: &str, request_id: &str) -> bool {
+        let Some(mirrored) = self.services.get(service) else {
+            return false;
+        };
+        let target = &mirrored.target;
+        if !target.methods.iter().any(|m| m.eq_ignore_ascii_case(method)) {
+            return false;
+        }
+        in_sample(request_id, target.sample_percent)
+    }
+
+    /// Sends the request to the service's target in the background and
+    /// records how its response compares with the client's
+    pub fn mirror(&self, service: &str, request: ShadowRequest, primary: PrimaryResponse) {
+        let Some(mirrored) = self.services.get(service).cloned() else {
+            return;
+        };
+        let Ok(slot) = self.slots.clone().try_acquire_owned() else {
+            mirrored.skipped.fetch_add(1, Ordering::Relaxed);
+            return;
+        };
+
+        let client = self.client.clone();
+        let max_diffs = self.max_diffs;
+        let service = service.to_string();
+        tokio::spawn(async move {
+            let _slot = slot;
+            let diff = shadow(&client, &mirrored.target, &request, &primary).await;
+            mirrored.mirrored.fetch_add(1, Ordering::Relaxed);
+
+            let Some(diff) = diff else {
+                mirrored.matched.fetch_add(1, Ordering::Relaxed);
+                return;
+            };
+            if diff.error.is_some() {
+                mirrored.failed.fetch_add(1, Ordering::Relaxed);
+            } else {
+                mirrored.mismatched.fetch_add(1, Ordering::Relaxed);
+            }
+            debug!(
+                service = %service,
+                request_id = %diff.request_id,
+                primary_status = diff.primary_status,
+                shadow_status = ?diff.shadow_status,
+                differences = ?diff.differences,
+                "Shadow response differs"
+            );
+            record(&mirrored.recent, diff, max_diffs);
+        });
+    }
+
+    pub fn snapshot(&self) -> Vec<MirrorStatus> {
+        let mut statuses: Vec<MirrorStatus> = self
+            .services
+            .iter()
+            .map(|(service, mirrored)| MirrorStatus {
+                service: service.clone(),
+                target: mirrored.target.base_url.clone(),
+                sample_percent: mirrored.target.sample_percent,
+                mirrored: mirrored.mirrored.load(Ordering::Relaxed),
+                matched: mirrored.matched.load(Ordering::Relaxed),
+                mismatched: mirrored.mismatched.load(Ordering::Relaxed),
+                failed: mirrored.failed.load(Ordering::Relaxed),
+                skipped: mirrored.skipped.load(Ordering::Relaxed),
+                recent_diffs: mirrored
+                    .recent
+                    .lock()
+                    .map(|recent| recent.iter().cloned().collect())
+                    .unwrap_or_default(),
+            })
+            .collect();
+        statuses.sort_by(|a, b| a.service.cmp(&b.service));
+        statuses
+    }
+}
+
+/// The comparison of the target's response with the primary's; None when
+/// they match
+async fn shadow(
+    client: &reqwest::Client,
+    target: &MirrorTarget,
+    request: &ShadowRequest,
+    primary: &PrimaryResponse,
+) -> Option<MirrorDiff> {
+    let url = format!("{}{}", target.base_url.trim_end_matches('/'), request.path);
+    let mut shadow_request = client
+        .request(request.method.clone(), &url)
+        .header(SHADOW_HEADER, "true");
+    for (name, value) in &request.headers {
+        shadow_request = shadow_request.header(name.as_str(), value.as_str());
+    }
+    if !request.body.is_empty() {
+        shadow_request = shadow_request.body(request.body.clone());
+    }
+
+    let started = Instant::now();
+    let result = async {
+        let response = shadow_request.send().await?;
+        let status = response.status().as_u16();
+        let body = response.bytes().await?;
+        Ok::<_, reqwest::Error>((status, body))
+    }
+    .await;
+    let shadow_latency = started.elapsed();
+
+    let (shadow_status, differences, error) = match result {
+        Ok((status, body)) => {
+            let differences = compare_bodies(&primary.body, &body, &target.ignore_fields);
+            if status == primary.status && differences.is_empty() {
+                return None;
+            }
+            (Some(status), differences, None)
+        }
+        Err(e) => {
+            warn!(url = %url, error = %e, "Shadow request failed");
+            (None, Vec::new(), Some(e.to_string()))
+        }
+    };
+
+    Some(MirrorDiff {
+        request_id: request.request_id.clone(),
+        method: request.method.to_string(),
+        path: request.path.split('?').next().unwrap_or_default().to_string(),
+        primary_status: primary.status,
+        shadow_status,
+        differences,
+        primary_latency_ms: primary.latency.as_millis() as u64,
+        shadow_latency_ms: shadow_latency.as_millis() as u64,
+        error,
+        recorded_at: Utc::now(),
+    })
+}
+
+fn record(recent: &Mutex<VecDeque<MirrorDiff>>, diff: MirrorDiff, max_diffs: usize) {
+    if max_diffs == 0 {
+        return;
+    }
+    if let Ok(mut recent) = recent.lock() {
+        while recent.len() >= max_diffs {
+            recent.pop_front();
+        }
+        recent.push_back(diff);
+    }
+}
+
+fn in_sample(request_id: &str, sample_percent: f64) -> bool {
+    if sample_percent <= 0.0 {
+        return false;
+    }
+    if sample_percent >= 100.0 {
+        return true;
+    }
+    let mut hasher = DefaultHasher::new();
+    request_id.hash(&mut hasher);
+    ((hasher.finish() % 10_000) as f64) < sample_percent * 100.0
+}
+
+/// Differing fields of two JSON bodies; bodies that aren't both JSON are
+/// compared byte for byte and reported as `body`
+pub fn compare_bodies(primary: &[u8], shadow: &[u8], ignore_fields: &[String]) -> Vec<String> {
+    match (
+        serde_json::from_slice::<Value>(primary),
+        serde_json::from_slice::<Value>(shadow),
+    ) {
+        (Ok(primary), Ok(shadow)) => {
+            let mut differences = Vec::new();
+            json_differences(&primary, &shadow, "", ignore_fields, &mut differences);
+            differences
+        }
+        _ if primary == shadow => Vec::new(),
+        _ => vec!["body".to_string()],
+    }
+}
+
+fn json_differences(primary: &Value, shadow: &Value, path: &str, ignore_fields: &[String], out: &mut Vec<String>) {
+    if out.len() >= MAX_DIFFERENCES || is_ignored(path, ignore_fields) {
+        return;
+    }
+
+    match (primary, shadow) {
+        (Value::Object(primary), Value::Object(shadow)) => {
+            let mut keys: Vec<&String> = primary.keys().chain(shadow.keys()).collect();
+            keys.sort();
+            keys.dedup();
+            for key in keys {
+                let field = if path.is_empty() {
+                    key.clone()
+                } else {
+                    format!("{}.{}", path, key)
+                };
+                match (primary.get(key), shadow.get(key)) {
+                    (Some(primary), Some(shadow)) => json_differences(primary, shadow, &field, ignore_fields, out),
+                    _ if is_ignored(&field, ignore_fields) => {}
+                    _ if out.len() < MAX_DIFFERENCES => out.push(field),
+                    _ => {}
+                }
+            }
+        }
+        (Value::Array(primary), Value::Array(shadow)) => {
+            if primary.len() != shadow.len() {
+                out.push(if path.is_empty() { "[]".to_string() } else { format!("{}[]", path) });
+                return;
+            }
+            for (index, (primary, shadow)) in primary.iter().zip(shadow).enumerate() {
+                json_differences(primary, shadow, &format!("{}[{}]", path, index), ignore_fields, out);
+            }
+        }
+        (primary, shadow) if primary != shadow => out.push(if path.is_empty() { "body".to_string() } else { path.to_string() }),
+        _ => {}
+    }
+}
+
+/// Ignored fields name a dotted path without array indexes, and cover
+/// everything beneath it, e.g. `data.updated_at` or `meta`
+fn is_ignored(path: &str, ignore_fields: &[String]) -> bool {
+    if path.is_empty() {
+        return false;
+    }
+    let mut field = String::with_capacity(path.len());
+    let mut in_index = false;
+    for c in path.chars() {
+        match c {
+            '[' => in_index = true,
+            ']' => in_index = false,
+            _ if !in_index => field.push(c),
+            _ => {}
+        }
+    }
+    ignore_fields.iter().any(|ignored| {
+        field == *ignored || field.strip_prefix(ignored.as_str()).is_some_and(|rest| rest.starts_with('.'))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config(sample_percent: f64, methods: &[&str]) -> MirrorConfig {
+        let mut config = crate::config::ApiGatewayConfig::development().mirror;
+        config.targets.insert(
+            "user".to_string(),
+            MirrorTarget {
+                base_url: "http://localhost:9082".to_string(),
+                sample_percent,
+                methods: methods.iter().map(|m| m.to_string()).collect(),
+                ignore_fields: vec!["meta".to_string()],
+            },
+        );
+        config
+    }
+
+    #[test]
+    fn test_sampling() {
+        let all = TrafficMirror::new(&config(100.0, &["GET"])).unwrap();
+        assert!(all.should_mirror("user", "GET", "req-1"));
+        assert!(all.should_mirror("user", "get", "req-2"));
+        assert!(!all.should_mirror("user", "POST", "req-1"));
+        assert!(!all.should_mirror("file", "GET", "req-1"));
+
+        let none = TrafficMirror::new(&config(0.0, &["GET"])).unwrap();
+        assert!(!none.should_mirror("user", "GET", "req-1"));
+
+        let some = TrafficMirror::new(&config(10.0, &["GET"])).unwrap();
+        let sampled = (0..10_000)
+            .filter(|i| some.should_mirror("user", "GET", &format!("req-{}", i)))
+            .count();
+        assert!((500..1500).contains(&sampled), "sampled {}", sampled);
+        assert_eq!(
+            some.should_mirror("user", "GET", "req-42"),
+            some.should_mirror("user", "GET", "req-42")
+        );
+    }
+
+    #[test]
+    fn test_compare_json_bodies() {
+        let ignore = vec!["meta".to_string(), "data.items.updated_at".to_string()];
+        let primary = br#"{"data": {"id": 1, "items": [{"name": "a", "updated_at": 1}], "tags": ["x"]}, "meta": {"took": 4}}"#;
+
+        let same = br#"{"meta": {"took": 9}, "data": {"tags": ["x"], "items": [{"updated_at": 2, "name": "a"}], "id": 1}}"#;
+        assert!(compare_bodies(primary, same, &ignore).is_empty());
+
+        let different = br#"{"data": {"id": "1", "items": [{"name": "b", "updated_at": 1}], "tags": ["x", "y"], "extra": true}}"#;
+        assert_eq!(
+            compare_bodies(primary, different, &ignore),
+            vec!["data.extra", "data.id", "data.items[0].name", "data.tags[]"]
+        );
+    }
+
+    #[test]
+    fn test_compare_other_bodies() {
+        assert!(compare_bodies(b"plain", b"plain", &[]).is_empty());
+        assert_eq!(compare_bodies(b"plain", b"other", &[]), vec!["body"]);
+        assert_eq!(compare_bodies(b"1", b"2", &[]), vec!["body"]);
+    }
+
+    #[test]
+    fn test_ignored_fields() {
+        let ignore = vec!["data.updated_at".to_string(), "meta".to_string()];
+        assert!(is_ignored("meta", &ignore));
+        assert!(is_ignored("meta.took", &ignore));
+        assert!(is_ignored("data.updated_at", &ignore));
+        assert!(is_ignored("data[3].updated_at", &ignore));
+        assert!(!is_ignored("metadata", &ignore));
+        assert!(!is_ignored("data.id", &ignore));
+    }
+
+    #[test]
+    fn test_recent_diffs_are_capped() {
+        let recent = Mutex::new(VecDeque::new());
+        for i in 0..5 {
+            let diff = MirrorDiff {
+                request_id: format!("req-{}", i),
+                method: "GET".to_string(),
+                path: "/api/v1/users".to_string(),
+                primary_status: 200,
+                shadow_status: Some(500),
+                differences: Vec::new(),
+                primary_latency_ms: 1,
+                shadow_latency_ms: 1,
+                error: None,
+                recorded_at: Utc::now(),
+            };
+            record(&recent, diff, 3);
+        }
+        let ids: Vec<String> = recent.lock().unwrap().iter().map(|d| d.request_id.clone()).collect();
+        assert_eq!(ids, vec!["req-2", "req-3", "req-4"]);
+    }
+}
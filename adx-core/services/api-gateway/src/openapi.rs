@@ -0,0 +1,396 @@
+//! Combined OpenAPI document of the platform.
+//!
+//! The gateway documents its own endpoints with utoipa and merges in each
+//! routed service's contribution, served by the service at `/openapi.json`.
+//! A service only documents the paths the routing table sends to it. Before
+//! the document goes to a client, operations the client's tenant is not
+//! entitled to are dropped.
+
+use serde_json::{Map, Value};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+use tracing::{debug, warn};
+use utoipa::OpenApi;
+
+use adx_shared::entitlements::{EntitlementClient, Entitlements};
+use crate::handlers;
+use crate::routing::{IntelligentRouter, OperationType, ServiceRoute};
+
+/// Where each service serves its contribution
+pub const SERVICE_DOCUMENT_PATH: &str = "/openapi.json";
+
+/// Operation extension naming the feature a tenant needs to call it
+pub const FEATURE_EXTENSION: &str = "x-adx-feature";
+
+/// Operation extension naming the marketplace module that serves it
+pub const MODULE_EXTENSION: &str = "x-adx-module";
+
+/// Operation extension naming the workflow the gateway starts for it
+pub const WORKFLOW_EXTENSION: &str = "x-adx-workflow";
+
+const HTTP_METHODS: [&str; 8] = ["get", "put", "post", "delete", "options", "head", "patch", "trace"];
+
+/// The gateway's own endpoints
+#[derive(OpenApi)]
+#[openapi(
+    info(
+        title = "ADX Core API",
+        description = "Every endpoint reachable through the API gateway"
+    ),
+    paths(
+        handlers::health_handler,
+        handlers::get_workflow_status,
+        handlers::cancel_workflow,
+    ),
+    components(schemas(handlers::HealthResponse, handlers::ServiceHealth, handlers::WorkflowAccepted)),
+    tags(
+        (name = "gateway", description = "API gateway"),
+        (name = "workflows", description = "Operations running as Temporal workflows")
+    )
+)]
+pub struct GatewayApi;
+
+/// Builds the combined document and filters it per tenant
+pub struct OpenApiRegistry {
+    router: Arc<IntelligentRouter>,
+    http_client: reqwest::Client,
+    entitlement_client: Arc<EntitlementClient>,
+    ttl: Duration,
+    cached: RwLock<Option<(Instant, Arc<Value>)>>,
+}
+
+impl OpenApiRegistry {
+    pub fn new(
+        router: Arc<IntelligentRouter>,
+        http_client: reqwest::Client,
+        entitlement_client: Arc<EntitlementClient>,
+        ttl: Duration,
+    ) -> Self {
+        Self {
+            router,
+            http_client,
+            entitlement_client,
+            ttl,
+            cached: RwLock::new(None),
+        }
+    }
+
+    /// The combined document; contributions are fetched again once it is
+    /// older than the TTL
+    pub async fn combined(&self) -> Arc<Value> {
+        if let Some((built_at, document)) = &*self.cached.read().await {
+            if built_at.elapsed() < self.ttl {
+                return document.clone();
+            }
+        }
+
+        let mut contributions = Vec::new();
+        for route in self.router.service_routes() {
+            let route = match self.router.service_route(&route.service_name) {
+                Ok(route) => route,
+                Err(e) => {
+                    debug!(service = %route.service_name, error = %e, "No endpoint for service's OpenAPI contribution");
+                    continue;
+                }
+            };
+            match self.fetch_contribution(&route).await {
+                Ok(contribution) => contributions.push((route.service_name.clone(), contribution)),
+                // Services that don't document themselves yet are left out
+                Err(e) => debug!(
+                    service = %route.service_name,
+                    error = %e,
+                    "No OpenAPI contribution from service"
+                ),
+            }
+        }
+
+        let document = Arc::new(combine(&self.router, &contributions));
+        *self.cached.write().await = Some((Instant::now(), document.clone()));
+        document
+    }
+
+    /// The combined document without the operations the tenant is not
+    /// entitled to. Without a tenant only ungated operations are left.
+    pub async fn for_tenant(&self, tenant_id: Option<&str>) -> Value {
+        let document = self.combined().await;
+
+        let entitlements = match tenant_id {
+            Some(tenant_id) => match self.entitlement_client.entitlements(tenant_id).await {
+                Ok(entitlements) => Some(entitlements),
+                Err(e) => {
+                    warn!(tenant_id = %tenant_id, error = %e, "Entitlement lookup failed, documenting ungated operations only");
+                    None
+                }
+            },
+            None => None,
+        };
+
+        filter_for_tenant(&document, entitlements.as_ref())
+    }
+
+    async fn fetch_contribution(&self, route: &ServiceRoute) -> Result<Value, reqwest::Error> {
+        self.http_client
+            .get(format!("{}{}", route.base_url, SERVICE_DOCUMENT_PATH))
+            .timeout(Duration::from_secs(route.timeout_seconds))
+            .send()
+            .await?
+            .error_for_status()?
+            .json()
+            .await
+    }
+}
+
+/// Merge services' contributions into the gateway's document. Paths a
+/// service documents outside its routed prefixes, and paths the gateway
+/// documents itself, are skipped.
+pub fn combine(router: &IntelligentRouter, contributions: &[(String, Value)]) -> Value {
+    let mut document = serde_json::to_value(GatewayApi::openapi()).unwrap_or_else(|_| Value::Object(Map::new()));
+
+    for (service, contribution) in contributions {
+        let prefixes = router.service_prefixes(service);
+
+        if let Some(paths) = contribution.get("paths").and_then(Value::as_object) {
+            let combined_paths = object_entry(&mut document, "paths");
+            for (path, item) in paths {
+                if !prefixes.iter().any(|prefix| is_under(path, prefix)) {
+                    warn!(service = %service, path = %path, "Skipping documented path not routed to the service");
+                    continue;
+                }
+                if combined_paths.contains_key(path) {
+                    continue;
+                }
+
+                let mut item = item.clone();
+                document_workflows(router, path, &mut item);
+                combined_paths.insert(path.clone(), item);
+            }
+        }
+
+        if let Some(schemas) = contribution.pointer("/components/schemas").and_then(Value::as_object) {
+            let components = object_entry(&mut document, "components");
+            let combined_schemas = components
+                .entry("schemas")
+                .or_insert_with(|| Value::Object(Map::new()));
+            if let Some(combined_schemas) = combined_schemas.as_object_mut() {
+                for (name, schema) in schemas {
+                    if combined_schemas.get(name).is_some_and(|existing| existing != schema) {
+                        warn!(service = %service, schema = %name, "Conflicting schema already documented, keeping the first");
+                        continue;
+                    }
+                    combined_schemas.insert(name.clone(), schema.clone());
+                }
+            }
+        }
+
+        if let Some(tags) = contribution.get("tags").and_then(Value::as_array) {
+            let combined_tags = document
+                .as_object_mut()
+                .map(|document| document.entry("tags").or_insert_with(|| Value::Array(Vec::new())))
+                .and_then(Value::as_array_mut);
+            if let Some(combined_tags) = combined_tags {
+                for tag in tags {
+                    if !combined_tags.iter().any(|existing| existing.get("name") == tag.get("name")) {
+                        combined_tags.push(tag.clone());
+                    }
+                }
+            }
+        }
+    }
+
+    document
+}
+
+/// The document without operations gated on a feature or module the
+/// tenant lacks, and without paths left with no operations
+pub fn filter_for_tenant(document: &Value, entitlements: Option<&Entitlements>) -> Value {
+    let mut document = document.clone();
+
+    if let Some(paths) = document.get_mut("paths").and_then(Value::as_object_mut) {
+        paths.retain(|_, item| {
+            let Some(item) = item.as_object_mut() else {
+                return false;
+            };
+            item.retain(|key, operation| !is_operation(key) || is_available(operation, entitlements));
+            item.keys().any(|key| is_operation(key))
+        });
+    }
+
+    document
+}
+
+/// Operations the router starts as asynchronous workflows answer with 202
+/// and the workflow's operation id instead of the service's own response
+fn document_workflows(router: &IntelligentRouter, path: &str, item: &mut Value) {
+    let Some(item) = item.as_object_mut() else {
+        return;
+    };
+
+    for (method, operation) in item.iter_mut().filter(|(key, _)| is_operation(key)) {
+        let Ok(method) = axum::http::Method::from_bytes(method.to_uppercase().as_bytes()) else {
+            continue;
+        };
+        let Ok(OperationType::Workflow(workflow)) = router.classify_operation(&method, path) else {
+            continue;
+        };
+        let Ok(route) = router.get_workflow_route(&workflow) else {
+            continue;
+        };
+        let Some(operation) = operation.as_object_mut() else {
+            continue;
+        };
+
+        operation.insert(WORKFLOW_EXTENSION.to_string(), Value::String(route.workflow_type.clone()));
+        if route.is_synchronous {
+            continue;
+        }
+
+        let responses = operation
+            .entry("responses")
+            .or_insert_with(|| Value::Object(Map::new()));
+        if let Some(responses) = responses.as_object_mut() {
+            responses.retain(|status, _| !status.starts_with('2'));
+            responses.insert(
+                "202".to_string(),
+                serde_json::json!({
+                    "description": "Started as a workflow; poll status_url for its result",
+                    "content": {
+                        "application/json": {
+                            "schema": { "$ref": "#/components/schemas/WorkflowAccepted" }
+                        }
+                    }
+                }),
+            );
+        }
+    }
+}
+
+fn is_available(operation: &Value, entitlements: Option<&Entitlements>) -> bool {
+    if let Some(feature) = operation.get(FEATURE_EXTENSION).and_then(Value::as_str) {
+        if !entitlements.is_some_and(|entitlements| entitlements.has_feature(feature)) {
+            return false;
+        }
+    }
+    if let Some(module_id) = operation.get(MODULE_EXTENSION).and_then(Value::as_str) {
+        if !entitlements.is_some_and(|entitlements| entitlements.has_module(module_id)) {
+            return false;
+        }
+    }
+    true
+}
+
+fn is_operation(key: &str) -> bool {
+    HTTP_METHODS.contains(&key)
+}
+
+fn is_under(path: &str, prefix: &str) -> bool {
+    path == prefix || path.strip_prefix(prefix).is_some_and(|rest| rest.starts_with('/'))
+}
+
+/// The object under `key`, created when missing
+fn object_entry<'a>(document: &'a mut Value, key: &str) -> &'a mut Map<String, Value> {
+    if !document.get(key).is_some_and(Value::is_object) {
+        document[key] = Value::Object(Map::new());
+    }
+    document[key].as_object_mut().expect("just made an object")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adx_shared::entitlements::AccessMode;
+    use serde_json::json;
+
+    fn entitlements(features: &[&str]) -> Entitlements {
+        Entitlements {
+            tenant_id: "tenant-1".to_string(),
+            subscription_tier: Some("Professional".to_string()),
+            license_status: Some("Active".to_string()),
+            active: true,
+            features: features.iter().map(|f| f.to_string()).collect(),
+            restricted_features: vec![],
+            quotas: vec![],
+            modules: vec![],
+            access_mode: AccessMode::Full,
+            grace_ends_at: None,
+            generated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_gateway_document() {
+        let document = serde_json::to_value(GatewayApi::openapi()).unwrap();
+
+        assert!(document["openapi"].as_str().unwrap().starts_with("3.1"));
+        assert!(document["paths"]["/health"]["get"].is_object());
+        assert!(document["paths"]["/api/v1/workflows/{operation_id}/cancel"]["post"].is_object());
+        assert!(document["components"]["schemas"]["WorkflowAccepted"].is_object());
+    }
+
+    #[test]
+    fn test_combine_keeps_routed_paths_only() {
+        let router = IntelligentRouter::new();
+        let contribution = json!({
+            "paths": {
+                "/api/v1/users/{id}": { "get": { "responses": { "200": { "description": "User" } } } },
+                "/api/v1/tenants": { "get": { "responses": { "200": { "description": "Tenants" } } } },
+                "/api/v1/users-export": { "get": { "responses": { "200": { "description": "Export" } } } }
+            },
+            "components": { "schemas": { "User": { "type": "object" } } },
+            "tags": [{ "name": "users" }]
+        });
+
+        let document = combine(&router, &[("user".to_string(), contribution)]);
+
+        assert!(document["paths"]["/api/v1/users/{id}"]["get"].is_object());
+        assert!(document["paths"].get("/api/v1/tenants").is_none());
+        assert!(document["paths"].get("/api/v1/users-export").is_none());
+        assert!(document["paths"]["/health"]["get"].is_object());
+        assert!(document["components"]["schemas"]["User"].is_object());
+        assert!(document["tags"].as_array().unwrap().iter().any(|tag| tag["name"] == "users"));
+    }
+
+    #[test]
+    fn test_combine_documents_workflow_operations() {
+        let router = IntelligentRouter::new();
+        let contribution = json!({
+            "paths": {
+                "/api/v1/tenants": {
+                    "post": { "responses": { "201": { "description": "Created" }, "400": { "description": "Invalid" } } }
+                }
+            }
+        });
+
+        let document = combine(&router, &[("tenant".to_string(), contribution)]);
+        let operation = &document["paths"]["/api/v1/tenants"]["post"];
+
+        assert_eq!(operation[WORKFLOW_EXTENSION], "create_tenant");
+        assert!(operation["responses"].get("201").is_none());
+        assert!(operation["responses"]["202"].is_object());
+        assert!(operation["responses"]["400"].is_object());
+    }
+
+    #[test]
+    fn test_filter_for_tenant() {
+        let document = json!({
+            "paths": {
+                "/api/v1/files": {
+                    "get": { "responses": {} },
+                    "post": { "x-adx-feature": "file_upload", "responses": {} }
+                },
+                "/api/v1/modules/crm/contacts": {
+                    "get": { "x-adx-module": "crm", "responses": {} }
+                }
+            }
+        });
+
+        let anonymous = filter_for_tenant(&document, None);
+        assert!(anonymous["paths"]["/api/v1/files"]["get"].is_object());
+        assert!(anonymous["paths"]["/api/v1/files"].get("post").is_none());
+        assert!(anonymous["paths"].get("/api/v1/modules/crm/contacts").is_none());
+
+        let entitled = filter_for_tenant(&document, Some(&entitlements(&["file_upload"])));
+        assert!(entitled["paths"]["/api/v1/files"]["post"].is_object());
+        assert!(entitled["paths"].get("/api/v1/modules/crm/contacts").is_none());
+    }
+}
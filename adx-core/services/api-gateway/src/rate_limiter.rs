@@ -1,17 +1,31 @@
 use std::sync::Arc;
 use std::time::Duration;
+use axum::http::{HeaderMap, HeaderValue};
 use redis::{AsyncCommands, Client as RedisClient};
 use tracing::{debug, warn, error};
 use serde::{Serialize, Deserialize};
 
+use adx_shared::entitlements::{EntitlementClient, Entitlements};
 use crate::config::RateLimitingConfig;
 use crate::error::{ApiGatewayError, ApiResult};
 
+/// License-service quotas a tenant's rate limits come from
+pub const REQUESTS_PER_MINUTE_QUOTA: &str = "api_requests_per_minute";
+pub const REQUESTS_PER_HOUR_QUOTA: &str = "api_calls_per_hour";
+pub const BURST_QUOTA: &str = "api_burst_requests";
+pub const CONCURRENT_WORKFLOWS_QUOTA: &str = "concurrent_workflows";
+
+/// Tenant and user ids of unauthenticated callers
+const ANONYMOUS: &str = "anonymous";
+
 /// Rate limiter with tenant and user awareness
 #[derive(Clone)]
 pub struct RateLimiter {
     redis_client: Arc<RedisClient>,
     config: RateLimitingConfig,
+    /// Tenants' plans, which set their tier; None holds every tenant to the
+    /// configured defaults
+    entitlement_client: Option<Arc<EntitlementClient>>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -22,6 +36,173 @@ pub struct RateLimitResult {
     pub remaining_minute: Option<u32>,
     pub remaining_hour: Option<u32>,
     pub current_usage: Option<u32>,
+    /// The window closest to its limit, reported in `RateLimit-*` headers
+    pub quota: Option<RateLimitQuota>,
+}
+
+/// One window's limit as reported to clients
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitQuota {
+    pub limit: u32,
+    pub remaining: u32,
+    /// Seconds until the window's counter starts over
+    pub reset_after: u64,
+    pub window_seconds: u64,
+}
+
+/// Limits a tenant's requests are held to as a whole, from its plan
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RateLimitTier {
+    /// The plan's subscription tier; None for the configured defaults
+    pub name: Option<String>,
+    pub requests_per_minute: u32,
+    pub requests_per_hour: u32,
+    /// Requests per 10 seconds
+    pub burst_limit: u32,
+    /// Workflows running at once; None when unlimited
+    pub concurrent_workflows: Option<u32>,
+}
+
+impl RateLimitTier {
+    pub fn defaults(config: &RateLimitingConfig) -> Self {
+        Self {
+            name: None,
+            requests_per_minute: config.requests_per_minute,
+            requests_per_hour: config.requests_per_hour,
+            burst_limit: config.burst_limit,
+            concurrent_workflows: None,
+        }
+    }
+
+    /// Quotas the plan doesn't have keep the configured defaults; unlimited
+    /// (-1) quotas aren't limited at all
+    pub fn from_entitlements(entitlements: &Entitlements, config: &RateLimitingConfig) -> Self {
+        let limit = |quota_name: &str| {
+            entitlements.quota(quota_name).map(|quota| match quota.quota_limit {
+                limit if limit < 0 => u32::MAX,
+                limit => u32::try_from(limit).unwrap_or(u32::MAX),
+            })
+        };
+
+        Self {
+            name: entitlements.subscription_tier.clone(),
+            requests_per_minute: limit(REQUESTS_PER_MINUTE_QUOTA).unwrap_or(config.requests_per_minute),
+            requests_per_hour: limit(REQUESTS_PER_HOUR_QUOTA).unwrap_or(config.requests_per_hour),
+            burst_limit: limit(BURST_QUOTA).unwrap_or(config.burst_limit),
+            concurrent_workflows: limit(CONCURRENT_WORKFLOWS_QUOTA).filter(|limit| *limit != u32::MAX),
+        }
+    }
+}
+
+/// A tenant's tier and where its counters stand, for the admin endpoint
+#[derive(Debug, Clone, Serialize)]
+pub struct TenantRateLimitStatus {
+    pub tenant_id: String,
+    pub tier: RateLimitTier,
+    pub counters: Vec<RateLimitCounter>,
+    pub running_workflows: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct RateLimitCounter {
+    pub key: String,
+    pub count: u32,
+    /// Seconds until the counter starts over
+    pub reset_after: u64,
+}
+
+/// A counter checked for one request
+#[derive(Debug)]
+struct RateLimitWindow {
+    name: &'static str,
+    key: String,
+    limit: u32,
+    seconds: u64,
+    count: u32,
+    reset_after: u64,
+}
+
+impl RateLimitWindow {
+    fn new(name: &'static str, key: String, limit: u32, seconds: u64) -> Self {
+        Self {
+            name,
+            key,
+            limit,
+            seconds,
+            count: 0,
+            reset_after: seconds,
+        }
+    }
+
+    fn remaining(&self) -> u32 {
+        self.limit.saturating_sub(self.count)
+    }
+}
+
+impl RateLimitQuota {
+    fn of(window: &RateLimitWindow) -> Self {
+        Self {
+            limit: window.limit,
+            remaining: window.remaining(),
+            reset_after: window.reset_after,
+            window_seconds: window.seconds,
+        }
+    }
+
+    /// The window with the fewest requests left; of those, the one that
+    /// takes longest to start over
+    fn closest(windows: &[RateLimitWindow]) -> Self {
+        windows
+            .iter()
+            .min_by(|a, b| {
+                a.remaining()
+                    .cmp(&b.remaining())
+                    .then(b.reset_after.cmp(&a.reset_after))
+            })
+            .map(Self::of)
+            .unwrap_or(Self {
+                limit: u32::MAX,
+                remaining: u32::MAX,
+                reset_after: 0,
+                window_seconds: 0,
+            })
+    }
+}
+
+impl RateLimitResult {
+    fn unlimited() -> Self {
+        Self {
+            allowed: true,
+            limit_type: None,
+            retry_after: None,
+            remaining_minute: None,
+            remaining_hour: None,
+            current_usage: None,
+            quota: None,
+        }
+    }
+
+    /// `RateLimit-Limit`, `RateLimit-Remaining` and `RateLimit-Reset` for
+    /// the window closest to its limit; nothing for unlimited ones
+    pub fn apply_headers(&self, headers: &mut HeaderMap) {
+        let Some(quota) = self.quota.as_ref().filter(|quota| quota.limit != u32::MAX) else {
+            return;
+        };
+        headers.insert("RateLimit-Limit", HeaderValue::from(quota.limit));
+        headers.insert("RateLimit-Remaining", HeaderValue::from(quota.remaining));
+        headers.insert("RateLimit-Reset", HeaderValue::from(quota.reset_after));
+        if let Ok(policy) = HeaderValue::from_str(&format!("{};w={}", quota.limit, quota.window_seconds)) {
+            headers.insert("RateLimit-Policy", policy);
+        }
+    }
+
+    /// The error for a request that wasn't allowed
+    pub fn to_error(&self) -> Option<ApiGatewayError> {
+        (!self.allowed).then(|| ApiGatewayError::RateLimitExceeded {
+            limit_type: self.limit_type.clone().unwrap_or_else(|| "unknown".to_string()),
+            retry_after: self.retry_after.unwrap_or(60),
+        })
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -56,10 +237,38 @@ impl RateLimiter {
         Ok(Self {
             redis_client: Arc::new(redis_client),
             config,
+            entitlement_client: None,
         })
     }
 
-    /// Check rate limit for a request
+    /// Holds tenants to their plan's limits
+    pub fn with_entitlements(mut self, entitlement_client: Arc<EntitlementClient>) -> Self {
+        self.entitlement_client = Some(entitlement_client);
+        self
+    }
+
+    /// Limits the tenant is held to: its plan's, or the configured
+    /// defaults when it has no plan or license-service can't be reached
+    pub async fn tier_for(&self, tenant_id: &str) -> RateLimitTier {
+        let defaults = RateLimitTier::defaults(&self.config);
+        let Some(entitlement_client) = &self.entitlement_client else {
+            return defaults;
+        };
+        if tenant_id == ANONYMOUS {
+            return defaults;
+        }
+
+        match entitlement_client.entitlements(tenant_id).await {
+            Ok(entitlements) => RateLimitTier::from_entitlements(&entitlements, &self.config),
+            Err(e) => {
+                warn!(tenant_id = tenant_id, error = %e, "Entitlement lookup failed, using default rate limits");
+                defaults
+            }
+        }
+    }
+
+    /// Check rate limit for a request. Each user is held to the configured
+    /// limits per endpoint, and the tenant as a whole to its tier's.
     pub async fn check_rate_limit(
         &self,
         tenant_id: &str,
@@ -67,90 +276,51 @@ impl RateLimiter {
         endpoint: &str,
     ) -> ApiResult<RateLimitResult> {
         if !self.config.enabled {
-            return Ok(RateLimitResult {
-                allowed: true,
-                limit_type: None,
-                retry_after: None,
-                remaining_minute: None,
-                remaining_hour: None,
-                current_usage: None,
-            });
+            return Ok(RateLimitResult::unlimited());
+        }
+
+        let mut windows = vec![
+            RateLimitWindow::new("per_minute", self.create_rate_limit_key(tenant_id, user_id, endpoint, "minute"), self.config.requests_per_minute, 60),
+            RateLimitWindow::new("per_hour", self.create_rate_limit_key(tenant_id, user_id, endpoint, "hour"), self.config.requests_per_hour, 3600),
+            RateLimitWindow::new("burst", self.create_rate_limit_key(tenant_id, user_id, endpoint, "burst"), self.config.burst_limit, 10),
+        ];
+        if tenant_id != ANONYMOUS {
+            let tier = self.tier_for(tenant_id).await;
+            windows.extend([
+                RateLimitWindow::new("tenant_per_minute", self.create_tenant_key(tenant_id, "minute"), tier.requests_per_minute, 60),
+                RateLimitWindow::new("tenant_per_hour", self.create_tenant_key(tenant_id, "hour"), tier.requests_per_hour, 3600),
+                RateLimitWindow::new("tenant_burst", self.create_tenant_key(tenant_id, "burst"), tier.burst_limit, 10),
+            ]);
         }
 
         let mut conn = self.redis_client.get_async_connection().await
             .map_err(|e| ApiGatewayError::RedisError {
                 message: format!("Failed to get Redis connection: {}", e),
             })?;
+        self.increment_counters(&mut conn, &mut windows).await?;
 
-        // Check minute-based rate limit
-        let minute_key = self.create_rate_limit_key(tenant_id, user_id, endpoint, "minute");
-        let minute_count = self.increment_counter(&mut conn, &minute_key, 60).await?;
-
-        if minute_count > self.config.requests_per_minute {
-            debug!(
-                tenant_id = tenant_id,
-                user_id = user_id,
-                endpoint = endpoint,
-                count = minute_count,
-                limit = self.config.requests_per_minute,
-                "Rate limit exceeded (per minute)"
-            );
-
-            return Ok(RateLimitResult {
-                allowed: false,
-                limit_type: Some("per_minute".to_string()),
-                retry_after: Some(60),
-                remaining_minute: Some(0),
-                remaining_hour: None,
-                current_usage: Some(minute_count),
-            });
-        }
-
-        // Check hour-based rate limit
-        let hour_key = self.create_rate_limit_key(tenant_id, user_id, endpoint, "hour");
-        let hour_count = self.increment_counter(&mut conn, &hour_key, 3600).await?;
-
-        if hour_count > self.config.requests_per_hour {
-            debug!(
-                tenant_id = tenant_id,
-                user_id = user_id,
-                endpoint = endpoint,
-                count = hour_count,
-                limit = self.config.requests_per_hour,
-                "Rate limit exceeded (per hour)"
-            );
+        let remaining_minute = windows[0].remaining();
+        let remaining_hour = windows[1].remaining();
 
-            return Ok(RateLimitResult {
-                allowed: false,
-                limit_type: Some("per_hour".to_string()),
-                retry_after: Some(3600),
-                remaining_minute: Some(self.config.requests_per_minute - minute_count),
-                remaining_hour: Some(0),
-                current_usage: Some(hour_count),
-            });
-        }
-
-        // Check burst limit
-        let burst_key = self.create_rate_limit_key(tenant_id, user_id, endpoint, "burst");
-        let burst_count = self.increment_counter(&mut conn, &burst_key, 10).await?; // 10 second window
-
-        if burst_count > self.config.burst_limit {
+        if let Some(exceeded) = windows.iter().find(|window| window.count > window.limit) {
             debug!(
                 tenant_id = tenant_id,
                 user_id = user_id,
                 endpoint = endpoint,
-                count = burst_count,
-                limit = self.config.burst_limit,
-                "Burst rate limit exceeded"
+                limit_type = exceeded.name,
+                count = exceeded.count,
+                limit = exceeded.limit,
+                "Rate limit exceeded"
             );
 
             return Ok(RateLimitResult {
                 allowed: false,
-                limit_type: Some("burst".to_string()),
-                retry_after: Some(10),
-                remaining_minute: Some(self.config.requests_per_minute - minute_count),
-                remaining_hour: Some(self.config.requests_per_hour - hour_count),
-                current_usage: Some(burst_count),
+                limit_type: Some(exceeded.name.to_string()),
+                retry_after: Some(exceeded.reset_after),
+                remaining_minute: Some(remaining_minute),
+                remaining_hour: Some(remaining_hour),
+                current_usage: Some(exceeded.count),
+                quota: Some(RateLimitQuota::of(exceeded)),
             });
         }
 
@@ -158,9 +328,7 @@ impl RateLimiter {
             tenant_id = tenant_id,
             user_id = user_id,
             endpoint = endpoint,
-            minute_count = minute_count,
-            hour_count = hour_count,
-            burst_count = burst_count,
+            counts = ?windows.iter().map(|window| window.count).collect::<Vec<_>>(),
             "Rate limit check passed"
         );
 
@@ -168,9 +336,10 @@ impl RateLimiter {
             allowed: true,
             limit_type: None,
             retry_after: None,
-            remaining_minute: Some(self.config.requests_per_minute - minute_count),
-            remaining_hour: Some(self.config.requests_per_hour - hour_count),
+            remaining_minute: Some(remaining_minute),
+            remaining_hour: Some(remaining_hour),
             current_usage: None,
+            quota: Some(RateLimitQuota::closest(&windows)),
         })
     }
 
@@ -182,14 +351,7 @@ impl RateLimiter {
         endpoint: &str,
     ) -> ApiResult<RateLimitResult> {
         if !self.config.enabled {
-            return Ok(RateLimitResult {
-                allowed: true,
-                limit_type: None,
-                retry_after: None,
-                remaining_minute: None,
-                remaining_hour: None,
-                current_usage: None,
-            });
+            return Ok(RateLimitResult::unlimited());
         }
 
         let mut conn = self.redis_client.get_async_connection().await
@@ -212,6 +374,7 @@ impl RateLimiter {
             remaining_minute: Some(self.config.requests_per_minute.saturating_sub(minute_count)),
             remaining_hour: Some(self.config.requests_per_hour.saturating_sub(hour_count)),
             current_usage: Some(minute_count.max(hour_count)),
+            quota: None,
         })
     }
 
@@ -266,47 +429,223 @@ impl RateLimiter {
         format!("rate_limit:{}:{}:{}:{}", tenant_id, user_id, endpoint, time_window)
     }
 
-    /// Increment counter with expiration
-    async fn increment_counter(
+    /// Create a key counting a whole tenant's requests
+    fn create_tenant_key(&self, tenant_id: &str, time_window: &str) -> String {
+        format!("rate_limit:{}:{}", tenant_id, time_window)
+    }
+
+    /// Key of the workflows a tenant has running, scored by when they stop
+    /// counting
+    fn create_workflow_slots_key(&self, tenant_id: &str) -> String {
+        format!("workflow_slots:{}", tenant_id)
+    }
+
+    /// Increment the windows' counters in one round trip. A counter's
+    /// expiry is set when it's created only, so a window starts over at a
+    /// fixed time rather than being pushed back by every request.
+    async fn increment_counters(
         &self,
         conn: &mut redis::aio::Connection,
-        key: &str,
-        expire_seconds: u64,
-    ) -> ApiResult<u32> {
-        // Use Redis pipeline for atomic increment and expire
-        let (count,): (u32,) = redis::pipe()
-            .incr(key, 1)
-            .expire(key, expire_seconds as i64)
+        windows: &mut [RateLimitWindow],
+    ) -> ApiResult<()> {
+        let mut pipe = redis::pipe();
+        for window in windows.iter() {
+            pipe.incr(&window.key, 1).ttl(&window.key);
+        }
+        let results: Vec<i64> = pipe
             .query_async(conn)
             .await
             .map_err(|e| ApiGatewayError::RedisError {
-                message: format!("Failed to increment counter: {}", e),
+                message: format!("Failed to increment counters: {}", e),
             })?;
 
-        Ok(count)
+        let mut expire = redis::pipe();
+        let mut expiring = false;
+        for (window, result) in windows.iter_mut().zip(results.chunks(2)) {
+            window.count = u32::try_from(result[0]).unwrap_or(u32::MAX);
+            if result[1] < 0 {
+                expire.expire(&window.key, window.seconds as i64).ignore();
+                expiring = true;
+            } else {
+                window.reset_after = result[1] as u64;
+            }
+        }
+        if expiring {
+            let _: () = expire
+                .query_async(conn)
+                .await
+                .map_err(|e| ApiGatewayError::RedisError {
+                    message: format!("Failed to set counter expiry: {}", e),
+                })?;
+        }
+
+        Ok(())
+    }
+
+    /// Counts a workflow against the tenant's concurrent workflow limit for
+    /// up to `workflow_slot_seconds`, or until it's released. The gateway
+    /// doesn't see every workflow finish, so a slot also frees itself.
+    pub async fn acquire_workflow_slot(&self, tenant_id: &str, workflow_id: &str) -> ApiResult<()> {
+        if !self.config.enabled || tenant_id == ANONYMOUS {
+            return Ok(());
+        }
+        let Some(limit) = self.tier_for(tenant_id).await.concurrent_workflows else {
+            return Ok(());
+        };
+
+        let mut conn = self.redis_client.get_async_connection().await
+            .map_err(|e| ApiGatewayError::RedisError {
+                message: format!("Failed to get Redis connection: {}", e),
+            })?;
+        let key = self.create_workflow_slots_key(tenant_id);
+        let now = chrono::Utc::now().timestamp();
+
+        let (running, earliest): (u64, Vec<(String, f64)>) = redis::pipe()
+            .zrembyscore(&key, "-inf", now).ignore()
+            .zcard(&key)
+            .zrange_withscores(&key, 0, 0)
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| ApiGatewayError::RedisError {
+                message: format!("Failed to count running workflows: {}", e),
+            })?;
+
+        if running >= u64::from(limit) {
+            let retry_after = earliest
+                .first()
+                .map(|(_, frees_at)| (*frees_at as i64 - now).max(1) as u64)
+                .unwrap_or(60);
+            debug!(tenant_id = tenant_id, running = running, limit = limit, "Concurrent workflow limit reached");
+            return Err(ApiGatewayError::RateLimitExceeded {
+                limit_type: "concurrent_workflows".to_string(),
+                retry_after,
+            });
+        }
+
+        let slot_seconds = self.config.workflow_slot_seconds;
+        let _: () = redis::pipe()
+            .zadd(&key, workflow_id, now + slot_seconds as i64).ignore()
+            .expire(&key, slot_seconds as i64).ignore()
+            .query_async(&mut conn)
+            .await
+            .map_err(|e| ApiGatewayError::RedisError {
+                message: format!("Failed to record running workflow: {}", e),
+            })?;
+
+        Ok(())
+    }
+
+    /// Stops counting a workflow that finished or was cancelled
+    pub async fn release_workflow_slot(&self, tenant_id: &str, workflow_id: &str) {
+        let key = self.create_workflow_slots_key(tenant_id);
+        let result: redis::RedisResult<()> = async {
+            let mut conn = self.redis_client.get_async_connection().await?;
+            conn.zrem(&key, workflow_id).await
+        }
+        .await;
+        if let Err(e) = result {
+            warn!(tenant_id = tenant_id, workflow_id = workflow_id, error = %e, "Failed to release workflow slot");
+        }
     }
-}
 
-/// Rate limiting middleware helper
-pub async fn check_rate_limit_middleware(
-    rate_limiter: &RateLimiter,
-    tenant_id: &str,
-    user_id: &str,
-    endpoint: &str,
-) -> Result<(), ApiGatewayError> {
-    let result = rate_limiter.check_rate_limit(tenant_id, user_id, endpoint).await?;
+    /// The tenant's tier and every counter it has running (admin operation)
+    pub async fn tenant_status(&self, tenant_id: &str) -> ApiResult<TenantRateLimitStatus> {
+        let tier = self.tier_for(tenant_id).await;
+        let mut conn = self.redis_client.get_async_connection().await
+            .map_err(|e| ApiGatewayError::RedisError {
+                message: format!("Failed to get Redis connection: {}", e),
+            })?;
+
+        let keys = self.tenant_counter_keys(&mut conn, tenant_id).await?;
+        let mut counters = Vec::with_capacity(keys.len());
+        if !keys.is_empty() {
+            let mut pipe = redis::pipe();
+            for key in &keys {
+                pipe.get(key).ttl(key);
+            }
+            let results: Vec<Option<i64>> = pipe.query_async(&mut conn).await
+                .map_err(|e| ApiGatewayError::RedisError {
+                    message: format!("Failed to read rate limit counters: {}", e),
+                })?;
+            for (key, result) in keys.into_iter().zip(results.chunks(2)) {
+                // Counters expiring between the two commands are left out
+                let Some(count) = result[0] else { continue };
+                counters.push(RateLimitCounter {
+                    key,
+                    count: u32::try_from(count).unwrap_or(u32::MAX),
+                    reset_after: result[1].unwrap_or(0).max(0) as u64,
+                });
+            }
+        }
+
+        let slots_key = self.create_workflow_slots_key(tenant_id);
+        let running_workflows: u64 = conn
+            .zcount(&slots_key, chrono::Utc::now().timestamp(), "+inf")
+            .await
+            .map_err(|e| ApiGatewayError::RedisError {
+                message: format!("Failed to count running workflows: {}", e),
+            })?;
+
+        Ok(TenantRateLimitStatus {
+            tenant_id: tenant_id.to_string(),
+            tier,
+            counters,
+            running_workflows,
+        })
+    }
+
+    /// Reset every request counter of a tenant and its users (admin
+    /// operation). Running workflows keep counting.
+    pub async fn reset_tenant_rate_limits(&self, tenant_id: &str) -> ApiResult<usize> {
+        let mut conn = self.redis_client.get_async_connection().await
+            .map_err(|e| ApiGatewayError::RedisError {
+                message: format!("Failed to get Redis connection: {}", e),
+            })?;
 
-    if !result.allowed {
-        let limit_type = result.limit_type.unwrap_or_else(|| "unknown".to_string());
-        let retry_after = result.retry_after.unwrap_or(60);
+        let keys = self.tenant_counter_keys(&mut conn, tenant_id).await?;
+        if !keys.is_empty() {
+            let _: () = conn.del(&keys).await
+                .map_err(|e| ApiGatewayError::RedisError {
+                    message: format!("Failed to delete rate limit keys: {}", e),
+                })?;
+        }
 
-        return Err(ApiGatewayError::RateLimitExceeded {
-            limit_type,
-            retry_after,
-        });
+        debug!(tenant_id = tenant_id, keys_deleted = keys.len(), "Tenant rate limits reset");
+        Ok(keys.len())
     }
 
-    Ok(())
+    /// SCAN rather than KEYS, since a tenant's keys are a small part of a
+    /// keyspace shared with every other tenant
+    async fn tenant_counter_keys(
+        &self,
+        conn: &mut redis::aio::Connection,
+        tenant_id: &str,
+    ) -> ApiResult<Vec<String>> {
+        let pattern = format!("rate_limit:{}:*", tenant_id);
+        let mut keys = Vec::new();
+        let mut cursor: u64 = 0;
+        loop {
+            let (next, batch): (u64, Vec<String>) = redis::cmd("SCAN")
+                .arg(cursor)
+                .arg("MATCH")
+                .arg(&pattern)
+                .arg("COUNT")
+                .arg(500)
+                .query_async(conn)
+                .await
+                .map_err(|e| ApiGatewayError::RedisError {
+                    message: format!("Failed to list rate limit keys: {}", e),
+                })?;
+            keys.extend(batch);
+            if next == 0 {
+                break;
+            }
+            cursor = next;
+        }
+        keys.sort();
+        keys.dedup();
+        Ok(keys)
+    }
 }
 
 #[cfg(test)]
@@ -320,12 +659,14 @@ mod tests {
             requests_per_minute: 100,
             requests_per_hour: 1000,
             burst_limit: 20,
+            workflow_slot_seconds: 900,
         };
 
         let redis_client = Arc::new(RedisClient::open("redis://localhost:6379").unwrap());
         let rate_limiter = RateLimiter {
             redis_client,
             config,
+            entitlement_client: None,
         };
 
         let key = rate_limiter.create_rate_limit_key("tenant1", "user1", "/api/test", "minute");
@@ -339,12 +680,14 @@ mod tests {
             requests_per_minute: 100,
             requests_per_hour: 1000,
             burst_limit: 20,
+            workflow_slot_seconds: 900,
         };
 
         let redis_client = Arc::new(RedisClient::open("redis://localhost:6379").unwrap());
         let rate_limiter = RateLimiter {
             redis_client,
             config,
+            entitlement_client: None,
         };
 
         let result = rate_limiter.check_rate_limit("tenant1", "user1", "/api/test").await;
@@ -360,4 +703,114 @@ mod tests {
             }
         }
     }
+
+    fn config() -> RateLimitingConfig {
+        RateLimitingConfig {
+            enabled: true,
+            requests_per_minute: 100,
+            requests_per_hour: 1000,
+            burst_limit: 20,
+            workflow_slot_seconds: 900,
+        }
+    }
+
+    fn quota(quota_name: &str, quota_limit: i64) -> adx_shared::entitlements::QuotaEntitlement {
+        adx_shared::entitlements::QuotaEntitlement {
+            quota_name: quota_name.to_string(),
+            quota_limit,
+            current_usage: 0,
+            remaining: quota_limit,
+            unit: "requests".to_string(),
+        }
+    }
+
+    fn window(limit: u32, count: u32, reset_after: u64, seconds: u64) -> RateLimitWindow {
+        let mut window = RateLimitWindow::new("per_minute", "key".to_string(), limit, seconds);
+        window.count = count;
+        window.reset_after = reset_after;
+        window
+    }
+
+    #[test]
+    fn test_tier_from_entitlements() {
+        let entitlements = Entitlements {
+            tenant_id: "tenant1".to_string(),
+            subscription_tier: Some("professional".to_string()),
+            license_status: Some("active".to_string()),
+            active: true,
+            features: vec![],
+            restricted_features: vec![],
+            quotas: vec![
+                quota(REQUESTS_PER_MINUTE_QUOTA, 600),
+                quota(REQUESTS_PER_HOUR_QUOTA, -1),
+                quota(CONCURRENT_WORKFLOWS_QUOTA, 10),
+            ],
+            modules: vec![],
+            access_mode: Default::default(),
+            grace_ends_at: None,
+            generated_at: chrono::Utc::now(),
+        };
+
+        let tier = RateLimitTier::from_entitlements(&entitlements, &config());
+        assert_eq!(tier.name.as_deref(), Some("professional"));
+        assert_eq!(tier.requests_per_minute, 600);
+        // Unlimited on the plan
+        assert_eq!(tier.requests_per_hour, u32::MAX);
+        // Not on the plan, so the configured default
+        assert_eq!(tier.burst_limit, 20);
+        assert_eq!(tier.concurrent_workflows, Some(10));
+
+        let unlimited = Entitlements {
+            quotas: vec![quota(CONCURRENT_WORKFLOWS_QUOTA, -1)],
+            ..entitlements
+        };
+        assert_eq!(RateLimitTier::from_entitlements(&unlimited, &config()).concurrent_workflows, None);
+    }
+
+    #[test]
+    fn test_headers_report_window_closest_to_limit() {
+        let windows = vec![window(100, 10, 30, 60), window(1000, 995, 1200, 3600), window(20, 1, 5, 10)];
+        let result = RateLimitResult {
+            quota: Some(RateLimitQuota::closest(&windows)),
+            ..RateLimitResult::unlimited()
+        };
+
+        let mut headers = HeaderMap::new();
+        result.apply_headers(&mut headers);
+        assert_eq!(headers["RateLimit-Limit"], "1000");
+        assert_eq!(headers["RateLimit-Remaining"], "5");
+        assert_eq!(headers["RateLimit-Reset"], "1200");
+        assert_eq!(headers["RateLimit-Policy"], "1000;w=3600");
+        assert!(result.to_error().is_none());
+    }
+
+    #[test]
+    fn test_unlimited_windows_send_no_headers() {
+        let result = RateLimitResult {
+            quota: Some(RateLimitQuota::of(&window(u32::MAX, 5, 30, 60))),
+            ..RateLimitResult::unlimited()
+        };
+
+        let mut headers = HeaderMap::new();
+        result.apply_headers(&mut headers);
+        assert!(headers.is_empty());
+    }
+
+    #[test]
+    fn test_exceeded_result_is_an_error() {
+        let result = RateLimitResult {
+            allowed: false,
+            limit_type: Some("tenant_per_minute".to_string()),
+            retry_after: Some(42),
+            ..RateLimitResult::unlimited()
+        };
+
+        match result.to_error() {
+            Some(ApiGatewayError::RateLimitExceeded { limit_type, retry_after }) => {
+                assert_eq!(limit_type, "tenant_per_minute");
+                assert_eq!(retry_after, 42);
+            }
+            other => panic!("unexpected {:?}", other),
+        }
+    }
 }
\ No newline at end of file
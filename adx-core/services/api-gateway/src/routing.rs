@@ -1,10 +1,24 @@
 use axum::http::{Method, Uri};
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
+use std::sync::Arc;
 use tracing::{debug, warn};
 
+use crate::discovery::ServiceDiscovery;
 use crate::error::{ApiGatewayError, ApiResult};
 
+/// First path segment after `/api/v1/` and the service it is routed to
+const SERVICE_SEGMENTS: &[(&str, &str)] = &[
+    ("auth", "auth"),
+    ("users", "user"),
+    ("tenants", "tenant"),
+    ("files", "file"),
+    ("workflows", "workflow"),
+    ("modules", "module"),
+    ("white-label", "white_label"),
+    ("branding", "white_label"),
+];
+
 /// Operation classification for intelligent routing
 #[derive(Debug, Clone, PartialEq)]
 pub enum OperationType {
@@ -50,6 +64,7 @@ pub enum WorkflowOperation {
     DataMigration,
     SystemMaintenance,
     ComplianceOperation,
+    BusinessTransaction,
     
     // Custom workflow
     Custom(String),
@@ -72,10 +87,22 @@ pub struct WorkflowRoute {
     pub is_synchronous: bool,
 }
 
+/// Where the events of a WebSocket subscription topic come from
+#[derive(Debug, Clone, PartialEq)]
+pub enum SubscriptionSource {
+    /// Workflow status, watched by the gateway through Temporal
+    WorkflowStatus,
+    /// A service's server-sent event stream, scoped to the subscriber's tenant
+    EventStream { service: String, path: String },
+}
+
 /// Intelligent router for API Gateway
 pub struct IntelligentRouter {
     service_routes: HashMap<String, ServiceRoute>,
     workflow_routes: HashMap<String, WorkflowRoute>,
+    subscription_routes: HashMap<String, SubscriptionSource>,
+    /// Where services' requests go; without it, to the default routes' URLs
+    discovery: Option<Arc<ServiceDiscovery>>,
 }
 
 impl IntelligentRouter {
@@ -83,12 +110,20 @@ impl IntelligentRouter {
         let mut router = Self {
             service_routes: HashMap::new(),
             workflow_routes: HashMap::new(),
+            subscription_routes: HashMap::new(),
+            discovery: None,
         };
         
         router.initialize_default_routes();
         router
     }
 
+    /// Send services' requests to the endpoints discovery finds for them
+    pub fn with_discovery(mut self, discovery: Arc<ServiceDiscovery>) -> Self {
+        self.discovery = Some(discovery);
+        self
+    }
+
     /// Initialize default routing rules
     fn initialize_default_routes(&mut self) {
         // Service routes
@@ -97,6 +132,8 @@ impl IntelligentRouter {
         self.add_service_route("file", "http://localhost:8083", 30);
         self.add_service_route("workflow", "http://localhost:8084", 60);
         self.add_service_route("tenant", "http://localhost:8085", 10);
+        self.add_service_route("module", "http://localhost:8086", 30);
+        self.add_service_route("white_label", "http://localhost:8089", 30);
 
         // Workflow routes
         self.add_workflow_route("user_registration", "user-task-queue", Some(30), false);
@@ -107,6 +144,17 @@ impl IntelligentRouter {
         self.add_workflow_route("file_upload", "file-task-queue", Some(180), false);
         self.add_workflow_route("file_processing", "file-task-queue", Some(300), false);
         self.add_workflow_route("bulk_operation", "bulk-task-queue", Some(600), false);
+        self.add_workflow_route("business_transaction_workflow", "workflow-service-queue", Some(120), false);
+
+        // Subscription routes
+        self.add_subscription_route("workflow", SubscriptionSource::WorkflowStatus);
+        self.add_subscription_route(
+            "tenant_events",
+            SubscriptionSource::EventStream {
+                service: "tenant".to_string(),
+                path: "/api/v1/admin/tenant-events/stream".to_string(),
+            },
+        );
     }
 
     /// Add a service route
@@ -140,6 +188,11 @@ impl IntelligentRouter {
         );
     }
 
+    /// Add a WebSocket subscription topic
+    pub fn add_subscription_route(&mut self, topic: &str, source: SubscriptionSource) {
+        self.subscription_routes.insert(topic.to_string(), source);
+    }
+
     /// Classify an operation based on HTTP method and path
     pub fn classify_operation(&self, method: &Method, path: &str) -> ApiResult<OperationType> {
         debug!(
@@ -265,6 +318,7 @@ impl IntelligentRouter {
             "data-migration" => WorkflowOperation::DataMigration,
             "system-maintenance" => WorkflowOperation::SystemMaintenance,
             "compliance-operation" => WorkflowOperation::ComplianceOperation,
+            "business-transaction" => WorkflowOperation::BusinessTransaction,
             _ => WorkflowOperation::Custom(workflow_type.to_string()),
         };
 
@@ -275,12 +329,7 @@ impl IntelligentRouter {
     pub fn get_service_route(&self, _operation: &DirectOperation, path: &str) -> ApiResult<ServiceRoute> {
         let service_name = self.extract_service_name(path)?;
         
-        self.service_routes
-            .get(&service_name)
-            .cloned()
-            .ok_or_else(|| ApiGatewayError::ServiceUnavailable {
-                service: service_name,
-            })
+        self.service_route(&service_name)
     }
 
     /// Get workflow route for workflow operations
@@ -303,6 +352,7 @@ impl IntelligentRouter {
             WorkflowOperation::DataMigration => "data_migration",
             WorkflowOperation::SystemMaintenance => "system_maintenance",
             WorkflowOperation::ComplianceOperation => "compliance_operation",
+            WorkflowOperation::BusinessTransaction => "business_transaction_workflow",
             WorkflowOperation::Custom(name) => name,
         };
 
@@ -319,16 +369,13 @@ impl IntelligentRouter {
         if path.starts_with("/api/v1/") {
             let parts: Vec<&str> = path.split('/').collect();
             if parts.len() >= 4 {
-                let service = match parts[3] {
-                    "auth" => "auth",
-                    "users" => "user",
-                    "tenants" => "tenant",
-                    "files" => "file",
-                    "workflows" => "workflow",
-                    _ => return Err(ApiGatewayError::InvalidRequest {
+                let service = SERVICE_SEGMENTS
+                    .iter()
+                    .find(|(segment, _)| *segment == parts[3])
+                    .map(|(_, service)| *service)
+                    .ok_or_else(|| ApiGatewayError::InvalidRequest {
                         message: format!("Unknown service in path: {}", path),
-                    }),
-                };
+                    })?;
                 return Ok(service.to_string());
             }
         }
@@ -343,6 +390,49 @@ impl IntelligentRouter {
         })
     }
 
+    /// Route of a service by name, to the endpoint whose turn it is
+    pub fn service_route(&self, service: &str) -> ApiResult<ServiceRoute> {
+        let mut route = self.service_routes
+            .get(service)
+            .cloned()
+            .ok_or_else(|| ApiGatewayError::ServiceUnavailable {
+                service: service.to_string(),
+            })?;
+        
+        if let Some(discovery) = &self.discovery {
+            if let Some(base_url) = discovery.resolve(service)? {
+                route.base_url = base_url;
+            }
+        }
+        Ok(route)
+    }
+
+    /// Where a subscription topic's events come from
+    pub fn get_subscription_route(&self, topic: &str) -> ApiResult<SubscriptionSource> {
+        self.subscription_routes
+            .get(topic)
+            .cloned()
+            .ok_or_else(|| ApiGatewayError::InvalidRequest {
+                message: format!("Unknown subscription topic: {}", topic),
+            })
+    }
+
+    /// Routed services, by name, with their default URLs
+    pub fn service_routes(&self) -> Vec<&ServiceRoute> {
+        let mut routes: Vec<&ServiceRoute> = self.service_routes.values().collect();
+        routes.sort_by(|a, b| a.service_name.cmp(&b.service_name));
+        routes
+    }
+
+    /// Path prefixes routed to the service, e.g. `/api/v1/users` for `user`
+    pub fn service_prefixes(&self, service: &str) -> Vec<String> {
+        SERVICE_SEGMENTS
+            .iter()
+            .filter(|(_, routed_to)| *routed_to == service)
+            .map(|(segment, _)| format!("/api/v1/{}", segment))
+            .collect()
+    }
+
     /// Build target URL for service routing
     pub fn build_service_url(&self, service_route: &ServiceRoute, path: &str) -> String {
         format!("{}{}", service_route.base_url, path)
@@ -382,6 +472,9 @@ mod tests {
 
         let result = router.classify_operation(&Method::POST, "/api/v1/workflows/create-tenant");
         assert!(matches!(result, Ok(OperationType::Workflow(WorkflowOperation::TenantCreation))));
+
+        let result = router.classify_operation(&Method::POST, "/api/v1/workflows/business-transaction");
+        assert!(matches!(result, Ok(OperationType::Workflow(WorkflowOperation::BusinessTransaction))));
     }
 
     #[test]
@@ -391,11 +484,23 @@ mod tests {
         assert_eq!(router.extract_service_name("/api/v1/users/123").unwrap(), "user");
         assert_eq!(router.extract_service_name("/api/v1/tenants").unwrap(), "tenant");
         assert_eq!(router.extract_service_name("/api/v1/files/upload").unwrap(), "file");
+        assert_eq!(router.extract_service_name("/api/v1/modules/assets/crm/1.0.0/remoteEntry.js").unwrap(), "module");
+        assert_eq!(router.extract_service_name("/api/v1/white-label/domains").unwrap(), "white_label");
+        assert_eq!(router.extract_service_name("/api/v1/branding/app.acme.com").unwrap(), "white_label");
         assert_eq!(router.extract_service_name("/health").unwrap(), "health");
 
         assert!(router.extract_service_name("/invalid/path").is_err());
     }
 
+    #[test]
+    fn test_service_prefixes() {
+        let router = IntelligentRouter::new();
+
+        assert_eq!(router.service_prefixes("user"), vec!["/api/v1/users"]);
+        assert_eq!(router.service_prefixes("white_label"), vec!["/api/v1/white-label", "/api/v1/branding"]);
+        assert!(router.service_prefixes("health").is_empty());
+    }
+
     #[test]
     fn test_service_route_retrieval() {
         let router = IntelligentRouter::new();
@@ -417,6 +522,18 @@ mod tests {
         assert!(!route.is_synchronous);
     }
 
+    #[test]
+    fn test_subscription_route_retrieval() {
+        let router = IntelligentRouter::new();
+
+        assert_eq!(router.get_subscription_route("workflow").unwrap(), SubscriptionSource::WorkflowStatus);
+        assert!(matches!(
+            router.get_subscription_route("tenant_events"),
+            Ok(SubscriptionSource::EventStream { service, .. }) if service == "tenant"
+        ));
+        assert!(router.get_subscription_route("unknown").is_err());
+    }
+
     #[test]
     fn test_url_building() {
         let router = IntelligentRouter::new();
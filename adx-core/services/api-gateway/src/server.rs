@@ -3,6 +3,7 @@ use axum::{
     routing::{get, post, put, delete, any},
     Router,
 };
+use std::net::SocketAddr;
 use std::sync::Arc;
 use std::time::Duration;
 use tower::ServiceBuilder;
@@ -12,26 +13,55 @@ use tower_http::{
     trace::TraceLayer,
     compression::CompressionLayer,
 };
-use tracing::{info, error};
+use tracing::{info, warn, error};
 
-use crate::config::ApiGatewayConfig;
+use crate::config::{ApiGatewayConfig, DiscoveryBackend};
+use crate::discovery::ServiceDiscovery;
 use crate::error::{ApiGatewayError, ApiResult};
+use crate::graphql;
+use crate::grpc::GrpcRouter;
 use crate::handlers::{
     AppState, health_handler, handle_request, get_workflow_status, 
-    cancel_workflow, signal_workflow
+    cancel_workflow, workflow_interaction, acme_challenge_handler, openapi_handler,
+    graphql_handler, websocket_handler, upstream_health_handler, get_tenant_rate_limits,
+    reset_tenant_rate_limits, webhook_handler, list_webhook_deliveries, replay_webhook_delivery,
+    mirror_status_handler, discovery_handler, refresh_discovery, maintenance_status_handler,
+    enable_platform_maintenance, disable_platform_maintenance, enable_tenant_maintenance,
+    disable_tenant_maintenance, set_kill_switch, remove_kill_switch
 };
 use crate::middleware::{
     MiddlewareState, request_id_middleware, auth_middleware, 
-    rate_limiting_middleware, tenant_middleware, cors_middleware, logging_middleware
+    rate_limiting_middleware, tenant_middleware, access_mode_middleware, network_policy_middleware,
+    custom_domain_middleware, idempotency_middleware, transform_middleware, validation_middleware,
+    maintenance_middleware, cors_middleware, logging_middleware, NetworkPolicyEnforcement
 };
+use crate::idempotency::IdempotencyStore;
+use crate::maintenance::MaintenanceControl;
+use crate::mirror::TrafficMirror;
+use crate::openapi::OpenApiRegistry;
 use crate::routing::IntelligentRouter;
 use crate::temporal_client::ApiGatewayTemporalClient;
 use crate::rate_limiter::RateLimiter;
+use crate::tls::{self, SniCertResolver};
+use crate::transform::TransformPolicies;
+use crate::upstreams::UpstreamRegistry;
+use crate::validation::RequestValidator;
+use crate::webhooks::WebhookReceiver;
+use crate::websocket::WEBSOCKET_PATH;
+use adx_shared::audit::AuditEmitter;
+use adx_shared::context_token::TenantContextSigner;
+use adx_shared::custom_domains::CustomDomainClient;
+use adx_shared::entitlements::EntitlementClient;
+use adx_shared::network_policy::NetworkPolicyClient;
+use adx_shared::signing_keys::SigningKeyClient;
+use adx_shared::telemetry::trace_http_requests;
 
 /// API Gateway Server
 pub struct ApiGatewayServer {
     config: Arc<ApiGatewayConfig>,
     app: Router,
+    /// Custom domain certificates, when the gateway terminates their TLS
+    sni_resolver: Option<(Arc<SniCertResolver>, Arc<CustomDomainClient>)>,
 }
 
 impl ApiGatewayServer {
@@ -46,13 +76,32 @@ impl ApiGatewayServer {
             ApiGatewayTemporalClient::new(config.temporal.clone()).await?
         );
         
+        // Tenants' plans, which set their rate limits, their access mode and
+        // the operations their API document shows
+        let entitlement_client = Arc::new(
+            EntitlementClient::from_url(&config.services.license_service.base_url)
+                .with_ttl(Duration::from_secs(config.licensing.entitlement_cache_seconds)),
+        );
+        
         // Initialize rate limiter
         let rate_limiter = Arc::new(
             RateLimiter::new(&config.redis.url, config.rate_limiting.clone()).await?
+                .with_entitlements(entitlement_client.clone())
         );
         
+        // Services' endpoint pools, looked up before the first request and
+        // kept current in the background
+        let discovery = Arc::new(ServiceDiscovery::new(&config.discovery, &config.services)?);
+        if config.discovery.backend != DiscoveryBackend::Static {
+            let refreshed = discovery.refresh().await;
+            if !refreshed.failed.is_empty() {
+                warn!(services = ?refreshed.failed, "Service lookup failed, using configured URLs until the next refresh");
+            }
+        }
+        discovery.clone().spawn_refresh();
+        
         // Initialize intelligent router
-        let router = Arc::new(IntelligentRouter::new());
+        let router = Arc::new(IntelligentRouter::new().with_discovery(discovery.clone()));
         
         // Initialize HTTP client for service communication
         let http_client = reqwest::Client::builder()
@@ -62,11 +111,124 @@ impl ApiGatewayServer {
                 message: format!("Failed to create HTTP client: {}", e),
             })?;
         
+        // Event streams stay open as long as their subscription does
+        let stream_client = reqwest::Client::builder()
+            .connect_timeout(Duration::from_secs(10))
+            .build()
+            .map_err(|e| ApiGatewayError::ConfigurationError {
+                message: format!("Failed to create streaming HTTP client: {}", e),
+            })?;
+        
+        let custom_domains = config.custom_domains.enabled.then(|| {
+            Arc::new(
+                CustomDomainClient::from_url(
+                    &config.services.white_label_service.base_url,
+                    &config.custom_domains.internal_token,
+                )
+                .with_ttl(Duration::from_secs(config.custom_domains.cache_seconds)),
+            )
+        });
+        let sni_resolver = custom_domains
+            .clone()
+            .filter(|_| config.custom_domains.tls_port.is_some())
+            .map(|client| (Arc::new(SniCertResolver::new()), client));
+        
+        // The API document drops operations tenants aren't entitled to,
+        // whether or not their access mode is enforced
+        let openapi = Arc::new(OpenApiRegistry::new(
+            router.clone(),
+            http_client.clone(),
+            entitlement_client.clone(),
+            Duration::from_secs(config.openapi.cache_seconds),
+        ));
+        
+        // Maintenance windows and kill switches, shared through Redis
+        let maintenance = if config.maintenance.enabled {
+            let maintenance = Arc::new(MaintenanceControl::new(
+                &config.redis.url,
+                config.maintenance.clone(),
+                http_client.clone(),
+                router.clone(),
+            )?);
+            if let Err(e) = maintenance.reload().await {
+                warn!(error = %e, "Failed to load maintenance windows, starting without them");
+            }
+            maintenance.clone().spawn_refresh();
+            Some(maintenance)
+        } else {
+            None
+        };
+        
         // Create middleware state
         let middleware_state = MiddlewareState {
             rate_limiter: rate_limiter.clone(),
             jwt_secret: config.auth.jwt_secret.clone(),
+            signing_keys: config.auth.rotating_signing_keys.then(|| {
+                Arc::new(
+                    SigningKeyClient::from_url(&config.services.security_service.base_url)
+                        .with_ttl(Duration::from_secs(config.auth.signing_key_cache_seconds)),
+                )
+            }),
             require_auth: config.auth.require_auth,
+            tenant_context_signer: Arc::new(TenantContextSigner::new(&config.auth.jwt_secret)),
+            entitlement_client: config.licensing.enforce_access_mode.then(|| entitlement_client.clone()),
+            network_policy: config.network_policy.enforce.then(|| NetworkPolicyEnforcement {
+                client: Arc::new(
+                    NetworkPolicyClient::from_url(&config.services.security_service.base_url)
+                        .with_ttl(Duration::from_secs(config.network_policy.cache_seconds)),
+                ),
+                audit: AuditEmitter::from_url(&config.services.security_service.base_url),
+                country_header: config.network_policy.country_header.clone(),
+                trust_forwarded_for: config.network_policy.trust_forwarded_for,
+                trusted_proxies: config.network_policy.trusted_proxies,
+                fail_open: config.network_policy.fail_open,
+            }),
+            custom_domains,
+            idempotency: if config.idempotency.enabled {
+                Some(Arc::new(IdempotencyStore::new(&config.redis.url, config.idempotency.clone())?))
+            } else {
+                None
+            },
+            transforms: config.transforms.policy_file.as_deref()
+                .map(TransformPolicies::from_file)
+                .transpose()?
+                .filter(|policies| !policies.is_empty())
+                .map(Arc::new),
+            validation: config.validation.enabled.then(|| {
+                Arc::new(RequestValidator::new(openapi.clone(), config.validation.max_errors))
+            }),
+            maintenance: maintenance.clone(),
+        };
+        
+        // Breakers and bulkheads for every routed service
+        let upstreams = Arc::new(UpstreamRegistry::new(&router, &config.upstreams));
+        
+        let grpc = if config.grpc.enabled {
+            Some(Arc::new(GrpcRouter::new(&config.grpc)?))
+        } else {
+            None
+        };
+        
+        // Inbound webhooks, dispatched to workflows or the module event bus
+        let webhooks = config.webhooks.integrations_file.as_deref()
+            .map(|path| {
+                WebhookReceiver::from_file(
+                    path,
+                    &config.redis.url,
+                    config.webhooks.clone(),
+                    temporal_client.clone(),
+                    http_client.clone(),
+                    &config.services.module_service.base_url,
+                )
+            })
+            .transpose()?
+            .map(Arc::new);
+        
+        // Shadow traffic for services being migrated to a new backend
+        let mirror = if config.mirror.targets.is_empty() {
+            None
+        } else {
+            Some(Arc::new(TrafficMirror::new(&config.mirror)?))
         };
         
         // Create application state
@@ -75,7 +237,16 @@ impl ApiGatewayServer {
             router,
             temporal_client,
             http_client,
+            stream_client,
             middleware_state: middleware_state.clone(),
+            openapi,
+            graphql_schema: config.graphql.enabled.then(|| graphql::build_schema(&config.graphql)),
+            upstreams,
+            grpc,
+            webhooks,
+            mirror,
+            discovery,
+            maintenance,
         };
         
         // Build the application router
@@ -83,7 +254,7 @@ impl ApiGatewayServer {
         
         info!("API Gateway server initialized successfully");
         
-        Ok(Self { config, app })
+        Ok(Self { config, app, sni_resolver })
     }
     
     /// Build the application router with all routes and middleware
@@ -93,26 +264,90 @@ impl ApiGatewayServer {
         info!("Building API Gateway router with middleware stack");
         
         // Create the main router
-        let app = Router::new()
+        let mut app = Router::new()
             // Health check endpoint (no auth required)
             .route("/health", get(health_handler))
             .route("/api/v1/health", get(health_handler))
+            .route("/gateway/health/upstreams", get(upstream_health_handler))
+            
+            // Services' endpoint pools (admin only)
+            .route("/gateway/discovery", get(discovery_handler))
+            .route("/gateway/discovery/refresh", post(refresh_discovery))
+            
+            // Rate limit tiers and counters per tenant (admin only)
+            .route("/gateway/rate-limits/:tenant_id", get(get_tenant_rate_limits).delete(reset_tenant_rate_limits))
+            
+            // Combined OpenAPI document, filtered to the caller's tenant
+            .route("/api/openapi.json", get(openapi_handler))
             
             // Workflow management endpoints
             .route("/api/v1/workflows/:operation_id/status", get(get_workflow_status))
             .route("/api/v1/workflows/:operation_id/cancel", post(cancel_workflow))
-            .route("/api/v1/workflows/:operation_id/signal/:signal_name", post(signal_workflow))
+            .route("/api/v1/workflows/:operation_id/signal/:signal_name", post(workflow_interaction))
+            .route("/api/v1/workflows/:operation_id/query/:query_name", post(workflow_interaction))
+            .route("/api/v1/workflows/:operation_id/interactions", get(workflow_interaction))
             
-            // Catch-all route for intelligent routing
-            .fallback(handle_request)
+            // Inbound webhooks and their archived deliveries (admin only)
+            .route("/webhooks/:integration", post(webhook_handler))
+            .route("/gateway/webhooks/:integration/deliveries", get(list_webhook_deliveries))
+            .route("/gateway/webhooks/:integration/deliveries/:delivery_id/replay", post(replay_webhook_delivery))
+            
+            // Shadow traffic counters and recent diffs (admin only)
+            .route("/gateway/mirror", get(mirror_status_handler))
+            
+            // Maintenance windows and route kill switches (admin only)
+            .route(
+                "/gateway/maintenance",
+                get(maintenance_status_handler).put(enable_platform_maintenance).delete(disable_platform_maintenance),
+            )
+            .route(
+                "/gateway/maintenance/tenants/:tenant_id",
+                put(enable_tenant_maintenance).delete(disable_tenant_maintenance),
+            )
+            .route("/gateway/kill-switches/:name", put(set_kill_switch).delete(remove_kill_switch))
             
+            // ACME HTTP-01 challenges for custom domain certificates
+            .route("/.well-known/acme-challenge/:token", get(acme_challenge_handler))
+            
+            // Catch-all route for intelligent routing
+            .fallback(handle_request);
+        
+        if app_state.graphql_schema.is_some() {
+            app = app.route("/graphql", post(graphql_handler));
+        }
+        
+        if app_state.config.websocket.enabled {
+            app = app.route(WEBSOCKET_PATH, get(websocket_handler));
+        }
+        
+        let app = app
             // Add application state
             .with_state(app_state.clone())
             
-            // Add basic middleware
+            // Add basic middleware; layers run bottom-up, so tenant context
+            // is resolved after authentication and request id assignment,
+            // custom domains are pinned to the resolved tenant, and the
+            // tenant's network policy, maintenance windows and access mode
+            // are checked once its context is known, requests are counted against the caller's
+            // and their tenant's rate limits, idempotency keys are scoped to
+            // the caller and fingerprint the request before route
+            // transformations, and requests are validated against the
+            // schema of the path they're routed by; the trace span wraps
+            // everything so the gateway's work joins the caller's trace
+            .layer(middleware::from_fn_with_state(app_state.middleware_state.clone(), validation_middleware))
+            .layer(middleware::from_fn_with_state(app_state.middleware_state.clone(), transform_middleware))
+            .layer(middleware::from_fn_with_state(app_state.middleware_state.clone(), idempotency_middleware))
+            .layer(middleware::from_fn_with_state(app_state.middleware_state.clone(), rate_limiting_middleware))
+            .layer(middleware::from_fn_with_state(app_state.middleware_state.clone(), access_mode_middleware))
+            .layer(middleware::from_fn_with_state(app_state.middleware_state.clone(), maintenance_middleware))
+            .layer(middleware::from_fn_with_state(app_state.middleware_state.clone(), network_policy_middleware))
+            .layer(middleware::from_fn_with_state(app_state.middleware_state.clone(), custom_domain_middleware))
+            .layer(middleware::from_fn_with_state(app_state.middleware_state.clone(), tenant_middleware))
+            .layer(middleware::from_fn_with_state(app_state.middleware_state.clone(), auth_middleware))
             .layer(middleware::from_fn(request_id_middleware))
             .layer(middleware::from_fn(cors_middleware))
-            .layer(middleware::from_fn(logging_middleware));
+            .layer(middleware::from_fn(logging_middleware))
+            .layer(middleware::from_fn(trace_http_requests));
         
         info!("API Gateway router built successfully");
         Ok(app)
@@ -137,8 +372,12 @@ impl ApiGatewayServer {
             "API Gateway server listening"
         );
         
-        // Start the server
-        axum::serve(listener, self.app)
+        if let (Some((resolver, client)), Some(tls_port)) = (self.sni_resolver, self.config.custom_domains.tls_port) {
+            Self::spawn_tls_listener(&self.config, self.app.clone(), resolver, client, tls_port);
+        }
+        
+        // Start the server; network policies need the peer address
+        axum::serve(listener, self.app.into_make_service_with_connect_info::<SocketAddr>())
             .await
             .map_err(|e| ApiGatewayError::InternalError {
                 message: format!("Server error: {}", e),
@@ -147,6 +386,40 @@ impl ApiGatewayServer {
         Ok(())
     }
     
+    /// Serve custom domains over TLS, choosing certificates by SNI
+    fn spawn_tls_listener(
+        config: &ApiGatewayConfig,
+        app: Router,
+        resolver: Arc<SniCertResolver>,
+        client: Arc<CustomDomainClient>,
+        tls_port: u16,
+    ) {
+        resolver
+            .clone()
+            .spawn_refresh(client, Duration::from_secs(config.custom_domains.cache_seconds.max(1)));
+        
+        let addr = format!("{}:{}", config.server.host, tls_port);
+        let rustls_config = axum_server::tls_rustls::RustlsConfig::from_config(Arc::new(tls::server_config(resolver)));
+        
+        tokio::spawn(async move {
+            let socket_addr: SocketAddr = match addr.parse() {
+                Ok(socket_addr) => socket_addr,
+                Err(e) => {
+                    error!(addr = %addr, error = %e, "Invalid custom domain TLS address");
+                    return;
+                }
+            };
+            
+            info!(addr = %addr, "Serving custom domains over TLS");
+            if let Err(e) = axum_server::bind_rustls(socket_addr, rustls_config)
+                .serve(app.into_make_service_with_connect_info::<SocketAddr>())
+                .await
+            {
+                error!(addr = %addr, error = %e, "Custom domain TLS listener failed");
+            }
+        });
+    }
+    
     /// Create a test server for integration tests
     #[cfg(test)]
     pub async fn test_server() -> ApiResult<Self> {
@@ -5,6 +5,9 @@ use uuid::Uuid;
 use serde::{Serialize, Deserialize};
 use chrono::{DateTime, Utc};
 
+use adx_shared::telemetry::{current_trace_headers, TRACEPARENT_HEADER};
+use adx_shared::temporal::TENANT_ID_ATTRIBUTE;
+
 use crate::config::TemporalConfig;
 use crate::error::{ApiGatewayError, ApiResult};
 
@@ -99,10 +102,15 @@ impl ApiGatewayTemporalClient {
         let run_id = Uuid::new_v4().to_string();
         let _started_at = Utc::now();
 
+        // Carried as the start request's header fields so the workflow's
+        // spans join the request's trace
+        let trace_headers = current_trace_headers();
+
         debug!(
             workflow_id = %workflow_id,
             workflow_type = workflow_type,
             run_id = %run_id,
+            traceparent = trace_headers.get(TRACEPARENT_HEADER).map(String::as_str).unwrap_or(""),
             "Workflow execution started (simulated)"
         );
 
@@ -169,6 +177,38 @@ impl ApiGatewayTemporalClient {
         })
     }
 
+    /// The tenant a workflow was started for, from the `TenantId` search
+    /// attribute of its latest run. None for runs started without one.
+    pub async fn get_workflow_tenant(&self, workflow_id: &str) -> ApiResult<Option<String>> {
+        let url = format!(
+            "http://{}/api/v1/namespaces/{}/workflows/{}",
+            self.server_address, self.namespace, workflow_id
+        );
+        let response = self.http_client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| ApiGatewayError::TemporalError {
+                message: format!("Failed to describe workflow: {}", e),
+            })?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Err(ApiGatewayError::WorkflowNotFound {
+                workflow_id: workflow_id.to_string(),
+            });
+        }
+        if !response.status().is_success() {
+            return Err(ApiGatewayError::TemporalError {
+                message: format!("Describing workflow {} failed with {}", workflow_id, response.status()),
+            });
+        }
+
+        let description: serde_json::Value = response.json().await.map_err(|e| ApiGatewayError::TemporalError {
+            message: format!("Invalid workflow description: {}", e),
+        })?;
+        Ok(workflow_tenant(&description))
+    }
+
     /// Cancel workflow execution
     pub async fn cancel_workflow(
         &self,
@@ -313,6 +353,16 @@ pub struct WorkflowProgress {
     pub message: Option<String>,
 }
 
+/// `TenantId` of a description from Temporal's HTTP API, in its shorthand
+/// payload encoding
+fn workflow_tenant(description: &serde_json::Value) -> Option<String> {
+    description
+        .pointer("/workflowExecutionInfo/searchAttributes/indexedFields")
+        .and_then(|fields| fields.get(TENANT_ID_ATTRIBUTE))
+        .and_then(|tenant_id| tenant_id.as_str())
+        .map(str::to_string)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -0,0 +1,158 @@
+// TLS termination for tenant custom domains
+//
+// When `custom_domains.tls_port` is set the gateway terminates TLS itself,
+// answering each handshake with the certificate of the domain the client
+// names in SNI. Certificates come from white-label-service with the domain
+// routes and are refreshed in the background, so renewed certificates are
+// picked up without a restart.
+
+use rustls::pki_types::{CertificateDer, PrivateKeyDer};
+use rustls::server::{ClientHello, ResolvesServerCert};
+use rustls::sign::CertifiedKey;
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use adx_shared::custom_domains::{normalize_host, CustomDomainClient, DomainRoutes};
+
+/// Picks a custom domain's certificate by SNI name
+#[derive(Debug, Default)]
+pub struct SniCertResolver {
+    certificates: RwLock<HashMap<String, Arc<CertifiedKey>>>,
+}
+
+impl SniCertResolver {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Replace the certificates with those of `routes`, returning how many
+    /// were loaded. A certificate that fails to parse is skipped; its
+    /// domain fails the handshake rather than taking the others down.
+    pub fn update(&self, routes: &DomainRoutes) -> usize {
+        let mut certificates = HashMap::new();
+        for route in routes.iter() {
+            let (Some(certificate_pem), Some(private_key_pem)) =
+                (route.certificate_pem.as_deref(), route.private_key_pem.as_deref())
+            else {
+                continue;
+            };
+
+            match certified_key(certificate_pem, private_key_pem) {
+                Ok(key) => {
+                    certificates.insert(normalize_host(&route.domain), Arc::new(key));
+                }
+                Err(e) => warn!(domain = %route.domain, error = %e, "Skipping unusable custom domain certificate"),
+            }
+        }
+
+        let loaded = certificates.len();
+        *self.certificates.write().unwrap_or_else(|e| e.into_inner()) = certificates;
+        loaded
+    }
+
+    /// The certificate for an SNI name
+    pub fn certificate_for(&self, server_name: &str) -> Option<Arc<CertifiedKey>> {
+        self.certificates
+            .read()
+            .unwrap_or_else(|e| e.into_inner())
+            .get(&normalize_host(server_name))
+            .cloned()
+    }
+
+    /// Keep the certificates in step with white-label-service
+    pub fn spawn_refresh(self: Arc<Self>, client: Arc<CustomDomainClient>, interval: Duration) -> JoinHandle<()> {
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match client.routes().await {
+                    Ok(routes) => {
+                        let loaded = self.update(&routes);
+                        debug!(domains = routes.len(), certificates = loaded, "Custom domain certificates refreshed");
+                    }
+                    Err(e) => warn!(error = %e, "Custom domain certificate refresh failed"),
+                }
+            }
+        })
+    }
+}
+
+impl ResolvesServerCert for SniCertResolver {
+    fn resolve(&self, client_hello: ClientHello<'_>) -> Option<Arc<CertifiedKey>> {
+        self.certificate_for(client_hello.server_name()?)
+    }
+}
+
+/// rustls server config answering with the resolver's certificates
+pub fn server_config(resolver: Arc<SniCertResolver>) -> rustls::ServerConfig {
+    // The provider is named rather than taken from the process default,
+    // which is ambiguous when another dependency enables a second one
+    let mut config = rustls::ServerConfig::builder_with_provider(Arc::new(rustls::crypto::ring::default_provider()))
+        .with_safe_default_protocol_versions()
+        .expect("ring supports the default protocol versions")
+        .with_no_client_auth()
+        .with_cert_resolver(resolver);
+    config.alpn_protocols = vec![b"h2".to_vec(), b"http/1.1".to_vec()];
+    config
+}
+
+fn certified_key(certificate_pem: &str, private_key_pem: &str) -> Result<CertifiedKey, String> {
+    let chain: Vec<CertificateDer<'static>> = rustls_pemfile::certs(&mut certificate_pem.as_bytes())
+        .collect::<Result<_, _>>()
+        .map_err(|e| format!("invalid certificate PEM: {}", e))?;
+    if chain.is_empty() {
+        return Err("no certificate in PEM".to_string());
+    }
+
+    let key = rustls_pemfile::pkcs8_private_keys(&mut private_key_pem.as_bytes())
+        .next()
+        .ok_or_else(|| "no PKCS#8 private key in PEM".to_string())?
+        .map_err(|e| format!("invalid private key PEM: {}", e))?;
+    let key = rustls::crypto::ring::sign::any_supported_type(&PrivateKeyDer::Pkcs8(key)).map_err(|e| e.to_string())?;
+
+    Ok(CertifiedKey::new(chain, key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adx_shared::custom_domains::DomainRoute;
+
+    fn route(domain: &str, certificate_pem: Option<String>, private_key_pem: Option<String>) -> DomainRoute {
+        DomainRoute {
+            domain: domain.to_string(),
+            tenant_id: "tenant-1".to_string(),
+            auto_redirect: true,
+            certificate_id: None,
+            certificate_pem,
+            private_key_pem,
+            certificate_expires_at: None,
+        }
+    }
+
+    #[test]
+    fn test_resolves_certificates_by_sni_name() {
+        let certificate = rcgen::generate_simple_self_signed(vec!["app.acme.com".to_string()]).unwrap();
+        let routes = DomainRoutes::new(vec![
+            route(
+                "app.acme.com",
+                Some(certificate.serialize_pem().unwrap()),
+                Some(certificate.serialize_private_key_pem()),
+            ),
+            route("broken.acme.com", Some("not a certificate".to_string()), Some("not a key".to_string())),
+            route("pending.acme.com", None, None),
+        ]);
+
+        let resolver = SniCertResolver::new();
+        assert_eq!(resolver.update(&routes), 1);
+        assert!(resolver.certificate_for("APP.acme.com").is_some());
+        assert!(resolver.certificate_for("broken.acme.com").is_none());
+        assert!(resolver.certificate_for("pending.acme.com").is_none());
+
+        assert_eq!(resolver.update(&DomainRoutes::default()), 0);
+        assert!(resolver.certificate_for("app.acme.com").is_none());
+    }
+}
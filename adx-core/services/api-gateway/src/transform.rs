@@ -0,0 +1,493 @@
+//! Declarative request and response transformations for proxied routes.
+//!
+//! Policies are read from a JSON file at startup, one per route pattern;
+//! the first policy whose pattern and methods match a request applies. A
+//! policy can strip and set request and response headers, rewrite the path
+//! prefix before the request is routed, and drop response fields callers
+//! without a permission shouldn't see. This keeps response shaping for a
+//! backend endpoint in one place instead of in every BFF that calls it.
+//!
+//! ```json
+//! [
+//!   {
+//!     "path": "/api/v1/users/**",
+//!     "methods": ["GET"],
+//!     "response": {
+//!       "remove_headers": ["x-powered-by"],
+//!       "field_filters": [{ "fields": ["data.email", "data.phone"], "unless_permission": "users:read_pii" }]
+//!     }
+//!   }
+//! ]
+//! ```
+
+use axum::{
+    body::Body,
+    extract::Request,
+    http::{header, uri::PathAndQuery, HeaderMap, HeaderName, HeaderValue, Method, Uri},
+    response::{IntoResponse, Response},
+};
+use serde::Deserialize;
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{debug, warn};
+
+use crate::error::{ApiGatewayError, ApiResult};
+use crate::middleware::RequestContext;
+
+/// Largest response body fields are filtered from
+const MAX_FILTERED_BODY_BYTES: usize = 16 * 1024 * 1024;
+
+/// Transformations for requests matching one route pattern
+#[derive(Debug, Clone, Deserialize)]
+pub struct RoutePolicy {
+    /// `*` matches one path segment and a trailing `**` any number, e.g.
+    /// `/api/v1/users/*/sessions` or `/api/v1/files/**`
+    pub path: String,
+    /// Methods the policy applies to; all of them when empty
+    #[serde(default)]
+    pub methods: Vec<String>,
+    #[serde(default)]
+    pub request: RequestTransform,
+    #[serde(default)]
+    pub response: ResponseTransform,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct RequestTransform {
+    #[serde(default)]
+    pub remove_headers: Vec<String>,
+    /// Values may use `{tenant_id}`, `{user_id}` and `{request_id}`
+    #[serde(default)]
+    pub set_headers: HashMap<String, String>,
+    pub rewrite_prefix: Option<PrefixRewrite>,
+}
+
+/// Replaces a leading part of the path, keeping the rest and the query
+#[derive(Debug, Clone, Deserialize)]
+pub struct PrefixRewrite {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ResponseTransform {
+    #[serde(default)]
+    pub remove_headers: Vec<String>,
+    /// Values may use `{tenant_id}`, `{user_id}` and `{request_id}`
+    #[serde(default)]
+    pub set_headers: HashMap<String, String>,
+    #[serde(default)]
+    pub field_filters: Vec<FieldFilter>,
+}
+
+/// JSON fields dropped from responses to callers lacking a permission
+#[derive(Debug, Clone, Deserialize)]
+pub struct FieldFilter {
+    /// Dotted paths; arrays on the way are filtered element by element, so
+    /// `data.email` covers both a single user and a list of them
+    pub fields: Vec<String>,
+    pub unless_permission: String,
+}
+
+/// The configured policies, in the order they're tried
+#[derive(Debug, Default)]
+pub struct TransformPolicies {
+    policies: Vec<Arc<RoutePolicy>>,
+}
+
+impl TransformPolicies {
+    /// Checks the policies' header names and methods up front, so a typo
+    /// fails startup instead of being skipped on every request
+    pub fn new(policies: Vec<RoutePolicy>) -> ApiResult<Self> {
+        for policy in &policies {
+            let invalid = |message: String| ApiGatewayError::ConfigurationError {
+                message: format!("Transformation policy for {}: {}", policy.path, message),
+            };
+
+            if !policy.path.starts_with('/') {
+                return Err(invalid("path must start with /".to_string()));
+            }
+            for method in &policy.methods {
+                Method::from_bytes(method.to_uppercase().as_bytes())
+                    .map_err(|_| invalid(format!("invalid method {}", method)))?;
+            }
+            let header_names = policy
+                .request
+                .remove_headers
+                .iter()
+                .chain(policy.request.set_headers.keys())
+                .chain(policy.response.remove_headers.iter())
+                .chain(policy.response.set_headers.keys());
+            for name in header_names {
+                HeaderName::from_bytes(name.as_bytes())
+                    .map_err(|_| invalid(format!("invalid header name {}", name)))?;
+            }
+            if let Some(rewrite) = &policy.request.rewrite_prefix {
+                if !rewrite.from.starts_with('/') || !rewrite.to.starts_with('/') {
+                    return Err(invalid("path rewrites must start with /".to_string()));
+                }
+            }
+        }
+
+        Ok(Self {
+            policies: policies.into_iter().map(Arc::new).collect(),
+        })
+    }
+
+    /// Policies from a JSON file holding a list of them
+    pub fn from_file(path: &str) -> ApiResult<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| ApiGatewayError::ConfigurationError {
+            message: format!("Failed to read transformation policies from {}: {}", path, e),
+        })?;
+        let policies: Vec<RoutePolicy> =
+            serde_json::from_str(&contents).map_err(|e| ApiGatewayError::ConfigurationError {
+                message: format!("Invalid transformation policies in {}: {}", path, e),
+            })?;
+        Self::new(policies)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.policies.is_empty()
+    }
+
+    pub fn policy_for(&self, method: &Method, path: &str) -> Option<Arc<RoutePolicy>> {
+        self.policies
+            .iter()
+            .find(|policy| {
+                (policy.methods.is_empty()
+                    || policy.methods.iter().any(|m| m.eq_ignore_ascii_case(method.as_str())))
+                    && path_matches(&policy.path, path)
+            })
+            .cloned()
+    }
+}
+
+pub(crate) fn path_matches(pattern: &str, path: &str) -> bool {
+    let mut pattern_segments = pattern.trim_end_matches('/').split('/');
+    let mut path_segments = path.trim_end_matches('/').split('/');
+
+    loop {
+        match (pattern_segments.next(), path_segments.next()) {
+            (Some("**"), _) => return pattern_segments.next().is_none(),
+            (Some("*"), Some(segment)) if !segment.is_empty() => {}
+            (Some(expected), Some(segment)) if expected == segment => {}
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+impl RequestTransform {
+    /// Applied before the request is routed, so a rewritten path is routed
+    /// as if the client had asked for it
+    pub fn apply(&self, request: &mut Request, context: &RequestContext) -> ApiResult<()> {
+        apply_headers(request.headers_mut(), &self.remove_headers, &self.set_headers, context);
+
+        let Some(rewrite) = &self.rewrite_prefix else {
+            return Ok(());
+        };
+        let path = request.uri().path();
+        let Some(rest) = path.strip_prefix(rewrite.from.trim_end_matches('/')) else {
+            return Ok(());
+        };
+        if !rest.is_empty() && !rest.starts_with('/') {
+            return Ok(());
+        }
+
+        let rewritten = format!("{}{}", rewrite.to.trim_end_matches('/'), rest);
+        let rewritten = match request.uri().query() {
+            Some(query) => format!("{}?{}", rewritten, query),
+            None => rewritten,
+        };
+        debug!(from = %path, to = %rewritten, "Rewriting request path");
+
+        let mut parts = request.uri().clone().into_parts();
+        parts.path_and_query = Some(PathAndQuery::try_from(rewritten.as_str()).map_err(|e| {
+            ApiGatewayError::InvalidRequest {
+                message: format!("Invalid rewritten path: {}", e),
+            }
+        })?);
+        *request.uri_mut() = Uri::from_parts(parts).map_err(|e| ApiGatewayError::InternalError {
+            message: format!("Failed to rewrite request path: {}", e),
+        })?;
+        Ok(())
+    }
+}
+
+impl ResponseTransform {
+    pub async fn apply(&self, mut response: Response, context: &RequestContext) -> Response {
+        apply_headers(response.headers_mut(), &self.remove_headers, &self.set_headers, context);
+
+        let hidden: Vec<&str> = self
+            .field_filters
+            .iter()
+            .filter(|filter| !has_permission(context, &filter.unless_permission))
+            .flat_map(|filter| filter.fields.iter().map(String::as_str))
+            .collect();
+        if hidden.is_empty() || !is_json(response.headers()) {
+            return response;
+        }
+
+        let (mut parts, body) = response.into_parts();
+        let body = match axum::body::to_bytes(body, MAX_FILTERED_BODY_BYTES).await {
+            Ok(body) => body,
+            // Passing the body on unfiltered would leak the hidden fields
+            Err(e) => {
+                warn!(error = %e, "Failed to read response body for field filtering");
+                return ApiGatewayError::InternalError {
+                    message: "Failed to filter response".to_string(),
+                }
+                .into_response();
+            }
+        };
+        let Ok(mut json) = serde_json::from_slice::<Value>(&body) else {
+            return Response::from_parts(parts, Body::from(body));
+        };
+
+        for field in hidden {
+            remove_field(&mut json, &field.split('.').collect::<Vec<_>>());
+        }
+
+        parts.headers.remove(header::CONTENT_LENGTH);
+        let body = serde_json::to_vec(&json).unwrap_or_else(|_| body.to_vec());
+        Response::from_parts(parts, Body::from(body))
+    }
+}
+
+fn apply_headers(
+    headers: &mut HeaderMap,
+    remove: &[String],
+    set: &HashMap<String, String>,
+    context: &RequestContext,
+) {
+    for name in remove {
+        headers.remove(name.as_str());
+    }
+    for (name, value) in set {
+        let value = render(value, context);
+        match (HeaderName::from_bytes(name.as_bytes()), HeaderValue::from_str(&value)) {
+            (Ok(name), Ok(value)) => {
+                headers.insert(name, value);
+            }
+            _ => warn!(header = %name, "Skipping header with an invalid value"),
+        }
+    }
+}
+
+/// Fills in the caller's ids; unknown ones are left empty
+fn render(template: &str, context: &RequestContext) -> String {
+    let tenant_id = context
+        .tenant_context
+        .as_ref()
+        .map(|t| t.tenant_id.as_str())
+        .unwrap_or_default();
+    let user_id = context
+        .user_context
+        .as_ref()
+        .map(|u| u.user_id.as_str())
+        .unwrap_or_default();
+
+    template
+        .replace("{tenant_id}", tenant_id)
+        .replace("{user_id}", user_id)
+        .replace("{request_id}", &context.request_id)
+}
+
+fn has_permission(context: &RequestContext, permission: &str) -> bool {
+    context.user_context.as_ref().is_some_and(|user| {
+        user.permissions
+            .iter()
+            .any(|p| p == permission || p == "*")
+    })
+}
+
+fn is_json(headers: &HeaderMap) -> bool {
+    headers
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|content_type| {
+            let mime = content_type.split(';').next().unwrap_or_default().trim();
+            mime == "application/json" || mime.ends_with("+json")
+        })
+}
+
+fn remove_field(value: &mut Value, path: &[&str]) {
+    match value {
+        Value::Array(items) => {
+            for item in items {
+                remove_field(item, path);
+            }
+        }
+        Value::Object(object) => match path {
+            [] => {}
+            [last] => {
+                object.remove(*last);
+            }
+            [first, rest @ ..] => {
+                if let Some(child) = object.get_mut(*first) {
+                    remove_field(child, rest);
+                }
+            }
+        },
+        _ => {}
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn policies(json: Value) -> TransformPolicies {
+        TransformPolicies::new(serde_json::from_value(json).unwrap()).unwrap()
+    }
+
+    fn context_with_permissions(permissions: &[&str]) -> RequestContext {
+        let mut context = RequestContext::with_request_id("req-1".to_string());
+        context.user_context = Some(adx_shared::UserContext {
+            user_id: "user-1".to_string(),
+            email: "user@example.com".to_string(),
+            display_name: None,
+            roles: vec![],
+            permissions: permissions.iter().map(|p| p.to_string()).collect(),
+            quotas: Default::default(),
+            preferences: Default::default(),
+            last_login: None,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        });
+        context
+    }
+
+    fn json_response(body: Value) -> Response {
+        let mut response = Response::new(Body::from(body.to_string()));
+        response
+            .headers_mut()
+            .insert(header::CONTENT_TYPE, HeaderValue::from_static("application/json"));
+        response
+    }
+
+    async fn body_json(response: Response) -> Value {
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        serde_json::from_slice(&body).unwrap()
+    }
+
+    #[test]
+    fn test_path_patterns() {
+        assert!(path_matches("/api/v1/users", "/api/v1/users"));
+        assert!(path_matches("/api/v1/users", "/api/v1/users/"));
+        assert!(path_matches("/api/v1/users/*", "/api/v1/users/42"));
+        assert!(!path_matches("/api/v1/users/*", "/api/v1/users"));
+        assert!(!path_matches("/api/v1/users/*", "/api/v1/users/42/sessions"));
+        assert!(path_matches("/api/v1/users/*/sessions", "/api/v1/users/42/sessions"));
+        assert!(path_matches("/api/v1/files/**", "/api/v1/files"));
+        assert!(path_matches("/api/v1/files/**", "/api/v1/files/a/b/c"));
+        assert!(!path_matches("/api/v1/files/**", "/api/v1/filesystem"));
+    }
+
+    #[test]
+    fn test_first_matching_policy_applies() {
+        let policies = policies(json!([
+            { "path": "/api/v1/users/*", "methods": ["get"] },
+            { "path": "/api/v1/users/**" }
+        ]));
+
+        let get = policies.policy_for(&Method::GET, "/api/v1/users/42").unwrap();
+        assert_eq!(get.path, "/api/v1/users/*");
+        let patch = policies.policy_for(&Method::PATCH, "/api/v1/users/42").unwrap();
+        assert_eq!(patch.path, "/api/v1/users/**");
+        assert!(policies.policy_for(&Method::GET, "/api/v1/files/1").is_none());
+    }
+
+    #[test]
+    fn test_invalid_policies_are_refused() {
+        let invalid_header: Vec<RoutePolicy> = serde_json::from_value(json!([
+            { "path": "/api/v1/users", "request": { "remove_headers": ["bad header"] } }
+        ]))
+        .unwrap();
+        assert!(TransformPolicies::new(invalid_header).is_err());
+
+        let relative_path: Vec<RoutePolicy> =
+            serde_json::from_value(json!([{ "path": "api/v1/users" }])).unwrap();
+        assert!(TransformPolicies::new(relative_path).is_err());
+    }
+
+    #[test]
+    fn test_request_headers_and_prefix_rewrite() {
+        let transform: RequestTransform = serde_json::from_value(json!({
+            "remove_headers": ["x-debug"],
+            "set_headers": { "x-caller": "{user_id}@{request_id}" },
+            "rewrite_prefix": { "from": "/api/v2/people", "to": "/api/v1/users" }
+        }))
+        .unwrap();
+
+        let mut request = Request::builder()
+            .uri("/api/v2/people/42?expand=roles")
+            .header("x-debug", "1")
+            .body(Body::empty())
+            .unwrap();
+        transform
+            .apply(&mut request, &context_with_permissions(&[]))
+            .unwrap();
+
+        assert_eq!(request.uri().path(), "/api/v1/users/42");
+        assert_eq!(request.uri().query(), Some("expand=roles"));
+        assert!(request.headers().get("x-debug").is_none());
+        assert_eq!(request.headers()["x-caller"], "user-1@req-1");
+
+        // Only whole segments are rewritten
+        let mut request = Request::builder()
+            .uri("/api/v2/peoplex")
+            .body(Body::empty())
+            .unwrap();
+        transform
+            .apply(&mut request, &context_with_permissions(&[]))
+            .unwrap();
+        assert_eq!(request.uri().path(), "/api/v2/peoplex");
+    }
+
+    #[tokio::test]
+    async fn test_fields_hidden_without_permission() {
+        let transform: ResponseTransform = serde_json::from_value(json!({
+            "remove_headers": ["x-powered-by"],
+            "field_filters": [{ "fields": ["data.email", "meta.internal"], "unless_permission": "users:read_pii" }]
+        }))
+        .unwrap();
+        let body = json!({
+            "data": [{ "id": "1", "email": "a@example.com" }, { "id": "2", "email": "b@example.com" }],
+            "meta": { "internal": true, "total": 2 }
+        });
+
+        let mut response = json_response(body.clone());
+        response
+            .headers_mut()
+            .insert("x-powered-by", HeaderValue::from_static("user-service"));
+        let filtered = transform
+            .apply(response, &context_with_permissions(&["users:read"]))
+            .await;
+        assert!(filtered.headers().get("x-powered-by").is_none());
+        assert_eq!(
+            body_json(filtered).await,
+            json!({ "data": [{ "id": "1" }, { "id": "2" }], "meta": { "total": 2 } })
+        );
+
+        let unfiltered = transform
+            .apply(json_response(body.clone()), &context_with_permissions(&["users:read_pii"]))
+            .await;
+        assert_eq!(body_json(unfiltered).await, body);
+    }
+
+    #[tokio::test]
+    async fn test_non_json_responses_are_left_alone() {
+        let transform: ResponseTransform = serde_json::from_value(json!({
+            "field_filters": [{ "fields": ["email"], "unless_permission": "users:read_pii" }]
+        }))
+        .unwrap();
+
+        let response = Response::new(Body::from("email,name"));
+        let response = transform.apply(response, &context_with_permissions(&[])).await;
+        let body = axum::body::to_bytes(response.into_body(), usize::MAX).await.unwrap();
+        assert_eq!(&body[..], b"email,name");
+    }
+}
@@ -0,0 +1,545 @@
+//! Circuit breakers and bulkheads for the backend services.
+//!
+//! Every call the gateway proxies to a service goes through that service's
+//! [`Upstream`]. A breaker opens after consecutive failures and refuses calls
+//! until the service has had time to recover, then lets a few trial calls
+//! through before closing again. A bulkhead caps the calls in flight, so a
+//! slow service ties up at most its own share of the gateway's connections
+//! and tasks instead of all of them.
+
+use axum::{
+    http::{HeaderValue, StatusCode},
+    response::{IntoResponse, Response},
+    Json,
+};
+use serde::Serialize;
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tokio::sync::{OwnedSemaphorePermit, Semaphore};
+use tracing::warn;
+
+use crate::config::{FallbackResponse, UpstreamConfig};
+use crate::error::ApiGatewayError;
+use crate::routing::IntelligentRouter;
+
+/// Header marking a response the gateway served in place of the service's
+pub const FALLBACK_HEADER: &str = "X-Gateway-Fallback";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CircuitState {
+    /// Calls go through
+    Closed,
+    /// Calls are refused until the open period is over
+    Open,
+    /// A limited number of trial calls decide whether to close again
+    HalfOpen,
+}
+
+#[derive(Debug)]
+struct BreakerState {
+    state: CircuitState,
+    consecutive_failures: u32,
+    opened_at: Option<Instant>,
+    trials_in_flight: u32,
+}
+
+#[derive(Debug)]
+struct CircuitBreaker {
+    failure_threshold: u32,
+    open_for: Duration,
+    half_open_requests: u32,
+    state: Mutex<BreakerState>,
+}
+
+impl CircuitBreaker {
+    fn new(failure_threshold: u32, open_for: Duration, half_open_requests: u32) -> Self {
+        Self {
+            failure_threshold,
+            open_for,
+            half_open_requests,
+            state: Mutex::new(BreakerState {
+                state: CircuitState::Closed,
+                consecutive_failures: 0,
+                opened_at: None,
+                trials_in_flight: 0,
+            }),
+        }
+    }
+
+    fn lock(&self) -> std::sync::MutexGuard<'_, BreakerState> {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+
+    /// Whether a call may go ahead, and if so whether it's a trial call.
+    /// Refusals carry how long until the circuit lets calls through again.
+    fn try_acquire(&self, now: Instant) -> Result<bool, Duration> {
+        let mut state = self.lock();
+
+        if state.state == CircuitState::Open {
+            let opened_at = state.opened_at.unwrap_or(now);
+            let elapsed = now.saturating_duration_since(opened_at);
+            if elapsed < self.open_for {
+                return Err(self.open_for - elapsed);
+            }
+            state.state = CircuitState::HalfOpen;
+            state.trials_in_flight = 0;
+        }
+
+        if state.state == CircuitState::HalfOpen {
+            if state.trials_in_flight >= self.half_open_requests {
+                return Err(Duration::from_secs(1));
+            }
+            state.trials_in_flight += 1;
+            return Ok(true);
+        }
+
+        Ok(false)
+    }
+
+    fn record(&self, success: bool, trial: bool, now: Instant) {
+        let mut state = self.lock();
+        if trial {
+            state.trials_in_flight = state.trials_in_flight.saturating_sub(1);
+        }
+
+        match (state.state, success) {
+            (CircuitState::Closed, true) => state.consecutive_failures = 0,
+            (CircuitState::Closed, false) => {
+                state.consecutive_failures += 1;
+                if state.consecutive_failures >= self.failure_threshold {
+                    state.state = CircuitState::Open;
+                    state.opened_at = Some(now);
+                }
+            }
+            (CircuitState::HalfOpen, true) if trial => {
+                state.state = CircuitState::Closed;
+                state.consecutive_failures = 0;
+                state.opened_at = None;
+            }
+            (CircuitState::HalfOpen, false) if trial => {
+                state.state = CircuitState::Open;
+                state.opened_at = Some(now);
+            }
+            // Calls that started before the circuit opened don't change it
+            _ => {}
+        }
+    }
+
+    /// Gives back a trial slot for a call whose outcome is unknown, e.g.
+    /// because the client went away
+    fn release(&self, trial: bool) {
+        if trial {
+            let mut state = self.lock();
+            state.trials_in_flight = state.trials_in_flight.saturating_sub(1);
+        }
+    }
+
+    /// Current state, without moving an expired open circuit to half-open
+    fn state(&self, now: Instant) -> (CircuitState, u32, Option<Duration>) {
+        let state = self.lock();
+        let retry_after = match (state.state, state.opened_at) {
+            (CircuitState::Open, Some(opened_at)) => {
+                Some(self.open_for.saturating_sub(now.saturating_duration_since(opened_at)))
+            }
+            _ => None,
+        };
+        (state.state, state.consecutive_failures, retry_after)
+    }
+}
+
+/// One backend service's breaker, bulkhead and fallback
+#[derive(Debug)]
+pub struct Upstream {
+    name: String,
+    breaker: CircuitBreaker,
+    slots: Arc<Semaphore>,
+    max_concurrent: usize,
+    fallback: Option<FallbackResponse>,
+    rejected: AtomicU64,
+}
+
+/// State of one service as reported by `/gateway/health/upstreams`
+#[derive(Debug, Serialize)]
+pub struct UpstreamStatus {
+    pub service: String,
+    pub state: CircuitState,
+    pub consecutive_failures: u32,
+    /// Seconds until an open circuit lets trial calls through
+    pub retry_after_seconds: Option<u64>,
+    pub in_flight: usize,
+    pub max_concurrent: usize,
+    /// Calls refused by the breaker or bulkhead since the gateway started
+    pub rejected: u64,
+    pub fallback_configured: bool,
+}
+
+/// The upstreams for every routed service
+#[derive(Debug)]
+pub struct UpstreamRegistry {
+    upstreams: HashMap<String, Arc<Upstream>>,
+    queue_timeout: Duration,
+}
+
+impl UpstreamRegistry {
+    pub fn new(router: &IntelligentRouter, config: &UpstreamConfig) -> Self {
+        let upstreams = router
+            .service_routes()
+            .into_iter()
+            .map(|route| {
+                let name = route.service_name.clone();
+                let max_concurrent = config
+                    .concurrency_overrides
+                    .get(&name)
+                    .copied()
+                    .unwrap_or(config.max_concurrent_requests)
+                    .max(1);
+                let upstream = Upstream {
+                    breaker: CircuitBreaker::new(
+                        config.failure_threshold,
+                        Duration::from_secs(config.open_seconds),
+                        config.half_open_requests,
+                    ),
+                    slots: Arc::new(Semaphore::new(max_concurrent)),
+                    max_concurrent,
+                    fallback: config.fallbacks.get(&name).cloned(),
+                    rejected: AtomicU64::new(0),
+                    name: name.clone(),
+                };
+                (name, Arc::new(upstream))
+            })
+            .collect();
+
+        Self {
+            upstreams,
+            queue_timeout: Duration::from_millis(config.queue_timeout_ms),
+        }
+    }
+
+    /// Permission to call a service. Services without an upstream, which
+    /// the router doesn't know, aren't guarded.
+    pub async fn acquire(&self, service: &str) -> Result<UpstreamPermit, UpstreamRejection> {
+        let Some(upstream) = self.upstreams.get(service) else {
+            return Ok(UpstreamPermit { guard: None });
+        };
+
+        let trial = upstream.breaker.try_acquire(Instant::now()).map_err(|retry_in| {
+            upstream.rejected.fetch_add(1, Ordering::Relaxed);
+            UpstreamRejection {
+                service: service.to_string(),
+                reason: RejectionReason::CircuitOpen {
+                    retry_after: retry_in.as_secs().max(1),
+                },
+                fallback: upstream.fallback.clone(),
+            }
+        })?;
+
+        let slot = if self.queue_timeout.is_zero() {
+            upstream.slots.clone().try_acquire_owned().ok()
+        } else {
+            tokio::time::timeout(self.queue_timeout, upstream.slots.clone().acquire_owned())
+                .await
+                .ok()
+                .and_then(Result::ok)
+        };
+        let Some(slot) = slot else {
+            upstream.breaker.release(trial);
+            upstream.rejected.fetch_add(1, Ordering::Relaxed);
+            warn!(service = %service, max_concurrent = upstream.max_concurrent, "Upstream at capacity");
+            return Err(UpstreamRejection {
+                service: service.to_string(),
+                reason: RejectionReason::Saturated,
+                fallback: upstream.fallback.clone(),
+            });
+        };
+
+        Ok(UpstreamPermit {
+            guard: Some(PermitGuard {
+                upstream: upstream.clone(),
+                trial,
+                _slot: slot,
+                recorded: false,
+            }),
+        })
+    }
+
+    pub fn snapshot(&self) -> Vec<UpstreamStatus> {
+        let now = Instant::now();
+        let mut statuses: Vec<UpstreamStatus> = self
+            .upstreams
+            .values()
+            .map(|upstream| {
+                let (state, consecutive_failures, retry_after) = upstream.breaker.state(now);
+                UpstreamStatus {
+                    service: upstream.name.clone(),
+                    state,
+                    consecutive_failures,
+                    retry_after_seconds: retry_after.map(|d| d.as_secs()),
+                    in_flight: upstream.max_concurrent - upstream.slots.available_permits(),
+                    max_concurrent: upstream.max_concurrent,
+                    rejected: upstream.rejected.load(Ordering::Relaxed),
+                    fallback_configured: upstream.fallback.is_some(),
+                }
+            })
+            .collect();
+        statuses.sort_by(|a, b| a.service.cmp(&b.service));
+        statuses
+    }
+}
+
+#[derive(Debug)]
+struct PermitGuard {
+    upstream: Arc<Upstream>,
+    trial: bool,
+    _slot: OwnedSemaphorePermit,
+    recorded: bool,
+}
+
+impl PermitGuard {
+    fn record(&mut self, success: bool) {
+        self.recorded = true;
+        self.upstream.breaker.record(success, self.trial, Instant::now());
+        if !success {
+            let (state, _, _) = self.upstream.breaker.state(Instant::now());
+            if state == CircuitState::Open {
+                warn!(service = %self.upstream.name, "Upstream circuit open");
+            }
+        }
+    }
+}
+
+impl Drop for PermitGuard {
+    fn drop(&mut self) {
+        if !self.recorded {
+            self.upstream.breaker.release(self.trial);
+        }
+    }
+}
+
+/// A bulkhead slot held for the length of one call. The call's outcome
+/// feeds the service's breaker; dropping the permit without one frees the
+/// slot and counts for nothing.
+#[derive(Debug)]
+pub struct UpstreamPermit {
+    guard: Option<PermitGuard>,
+}
+
+impl UpstreamPermit {
+    pub fn success(mut self) {
+        if let Some(guard) = self.guard.as_mut() {
+            guard.record(true);
+        }
+    }
+
+    /// Timeouts, refused connections and the like
+    pub fn failure(mut self) {
+        if let Some(guard) = self.guard.as_mut() {
+            guard.record(false);
+        }
+    }
+
+    /// 5xx responses count against the service; anything else, including
+    /// 4xx, means it's up
+    pub fn record_status(self, status: u16) {
+        if status >= 500 {
+            self.failure()
+        } else {
+            self.success()
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RejectionReason {
+    CircuitOpen { retry_after: u64 },
+    Saturated,
+}
+
+/// A call refused without reaching the service
+#[derive(Debug)]
+pub struct UpstreamRejection {
+    pub service: String,
+    pub reason: RejectionReason,
+    fallback: Option<FallbackResponse>,
+}
+
+impl From<UpstreamRejection> for ApiGatewayError {
+    fn from(rejection: UpstreamRejection) -> Self {
+        match rejection.reason {
+            RejectionReason::CircuitOpen { retry_after } => ApiGatewayError::CircuitOpen {
+                service: rejection.service,
+                retry_after,
+            },
+            RejectionReason::Saturated => ApiGatewayError::UpstreamSaturated {
+                service: rejection.service,
+            },
+        }
+    }
+}
+
+/// The service's configured fallback if it has one, otherwise the error
+impl IntoResponse for UpstreamRejection {
+    fn into_response(self) -> Response {
+        let Some(fallback) = self.fallback.clone() else {
+            return ApiGatewayError::from(self).into_response();
+        };
+
+        let status = StatusCode::from_u16(fallback.status).unwrap_or(StatusCode::SERVICE_UNAVAILABLE);
+        let mut response = (status, Json(fallback.body)).into_response();
+        if let Ok(service) = HeaderValue::from_str(&self.service) {
+            response.headers_mut().insert(FALLBACK_HEADER, service);
+        }
+        if let RejectionReason::CircuitOpen { retry_after } = self.reason {
+            response
+                .headers_mut()
+                .insert("Retry-After", HeaderValue::from(retry_after));
+        }
+        response
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::ApiGatewayConfig;
+
+    fn registry(configure: impl FnOnce(&mut UpstreamConfig)) -> UpstreamRegistry {
+        let config = ApiGatewayConfig::development();
+        let router = IntelligentRouter::new();
+        let mut upstreams = config.upstreams.clone();
+        upstreams.failure_threshold = 2;
+        upstreams.open_seconds = 30;
+        upstreams.queue_timeout_ms = 0;
+        configure(&mut upstreams);
+        UpstreamRegistry::new(&router, &upstreams)
+    }
+
+    #[test]
+    fn test_breaker_opens_after_consecutive_failures() {
+        let breaker = CircuitBreaker::new(2, Duration::from_secs(30), 1);
+        let now = Instant::now();
+
+        assert_eq!(breaker.try_acquire(now), Ok(false));
+        breaker.record(false, false, now);
+        breaker.record(true, false, now);
+        breaker.record(false, false, now);
+        assert_eq!(breaker.state(now).0, CircuitState::Closed);
+
+        breaker.record(false, false, now);
+        assert_eq!(breaker.state(now).0, CircuitState::Open);
+        assert_eq!(
+            breaker.try_acquire(now + Duration::from_secs(10)),
+            Err(Duration::from_secs(20))
+        );
+    }
+
+    #[test]
+    fn test_breaker_half_open_trials() {
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(30), 1);
+        let opened = Instant::now();
+        breaker.record(false, false, opened);
+
+        // One trial at a time once the open period is over
+        let later = opened + Duration::from_secs(31);
+        assert_eq!(breaker.try_acquire(later), Ok(true));
+        assert!(breaker.try_acquire(later).is_err());
+
+        // A failed trial reopens the circuit
+        breaker.record(false, true, later);
+        assert_eq!(breaker.state(later).0, CircuitState::Open);
+
+        // A successful one closes it
+        let much_later = later + Duration::from_secs(31);
+        assert_eq!(breaker.try_acquire(much_later), Ok(true));
+        breaker.record(true, true, much_later);
+        assert_eq!(breaker.state(much_later).0, CircuitState::Closed);
+        assert_eq!(breaker.try_acquire(much_later), Ok(false));
+    }
+
+    #[test]
+    fn test_abandoned_trial_frees_its_slot() {
+        let breaker = CircuitBreaker::new(1, Duration::from_secs(0), 1);
+        let now = Instant::now();
+        breaker.record(false, false, now);
+
+        assert_eq!(breaker.try_acquire(now), Ok(true));
+        breaker.release(true);
+        assert_eq!(breaker.try_acquire(now), Ok(true));
+    }
+
+    #[tokio::test]
+    async fn test_open_circuit_rejects_calls() {
+        let upstreams = registry(|_| {});
+
+        upstreams.acquire("user").await.unwrap().record_status(502);
+        upstreams.acquire("user").await.unwrap().failure();
+
+        let rejection = upstreams.acquire("user").await.unwrap_err();
+        assert!(matches!(rejection.reason, RejectionReason::CircuitOpen { .. }));
+        assert!(matches!(
+            ApiGatewayError::from(rejection),
+            ApiGatewayError::CircuitOpen { .. }
+        ));
+
+        // Other services are unaffected
+        assert!(upstreams.acquire("tenant").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_client_errors_dont_open_circuit() {
+        let upstreams = registry(|_| {});
+
+        for _ in 0..5 {
+            upstreams.acquire("user").await.unwrap().record_status(404);
+        }
+
+        assert!(upstreams.acquire("user").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_bulkhead_limits_calls_in_flight() {
+        let upstreams = registry(|config| {
+            config.concurrency_overrides.insert("file".to_string(), 1);
+        });
+
+        let held = upstreams.acquire("file").await.unwrap();
+        let rejection = upstreams.acquire("file").await.unwrap_err();
+        assert_eq!(rejection.reason, RejectionReason::Saturated);
+
+        let status = upstreams.snapshot().into_iter().find(|s| s.service == "file").unwrap();
+        assert_eq!(status.in_flight, 1);
+        assert_eq!(status.rejected, 1);
+
+        drop(held);
+        assert!(upstreams.acquire("file").await.is_ok());
+    }
+
+    #[tokio::test]
+    async fn test_rejection_serves_configured_fallback() {
+        let upstreams = registry(|config| {
+            config.failure_threshold = 1;
+            config.fallbacks.insert(
+                "module".to_string(),
+                FallbackResponse {
+                    status: 200,
+                    body: serde_json::json!({ "data": [] }),
+                },
+            );
+        });
+
+        upstreams.acquire("module").await.unwrap().failure();
+        let response = upstreams.acquire("module").await.unwrap_err().into_response();
+
+        assert_eq!(response.status(), StatusCode::OK);
+        assert_eq!(response.headers()[FALLBACK_HEADER], "module");
+        assert!(response.headers().contains_key("Retry-After"));
+    }
+
+    #[tokio::test]
+    async fn test_unknown_services_are_unguarded() {
+        let upstreams = registry(|_| {});
+        assert!(upstreams.acquire("unknown").await.is_ok());
+    }
+}
@@ -0,0 +1,570 @@
+//! Request validation against the combined OpenAPI document.
+//!
+//! Operations a service documents with a JSON request body or query
+//! parameters have their requests checked at the gateway, so malformed
+//! requests are refused with the offending fields listed instead of
+//! reaching the service. Schemas are compiled from the same document
+//! `/api/openapi.json` serves and compiled again whenever it's rebuilt.
+//! Requests to undocumented operations pass through unchecked.
+
+use axum::extract::Query;
+use axum::http::{Method, Uri};
+use jsonschema::{error::ValidationErrorKind, Draft, JSONSchema};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tokio::sync::RwLock;
+use tracing::warn;
+
+use crate::error::ValidationError;
+use crate::openapi::OpenApiRegistry;
+
+/// Compiles the combined document's request schemas and keeps them until
+/// the document changes
+pub struct RequestValidator {
+    openapi: Arc<OpenApiRegistry>,
+    max_errors: usize,
+    compiled: RwLock<Option<(Arc<Value>, Arc<OperationSchemas>)>>,
+}
+
+impl RequestValidator {
+    pub fn new(openapi: Arc<OpenApiRegistry>, max_errors: usize) -> Self {
+        Self {
+            openapi,
+            max_errors,
+            compiled: RwLock::new(None),
+        }
+    }
+
+    /// Most field errors reported for one request
+    pub fn max_errors(&self) -> usize {
+        self.max_errors
+    }
+
+    pub async fn schemas(&self) -> Arc<OperationSchemas> {
+        let document = self.openapi.combined().await;
+        if let Some((source, schemas)) = &*self.compiled.read().await {
+            if Arc::ptr_eq(source, &document) {
+                return schemas.clone();
+            }
+        }
+
+        let schemas = Arc::new(OperationSchemas::compile(&document));
+        *self.compiled.write().await = Some((document, schemas.clone()));
+        schemas
+    }
+}
+
+/// Request schemas of every documented operation
+pub struct OperationSchemas {
+    /// Fewest path parameters first, so literal paths win over templates
+    operations: Vec<OperationSchema>,
+}
+
+impl OperationSchemas {
+    pub fn compile(document: &Value) -> Self {
+        let components = document.get("components").cloned().unwrap_or_else(|| json!({}));
+        let mut operations = Vec::new();
+
+        for (path, item) in document.get("paths").and_then(Value::as_object).into_iter().flatten() {
+            let shared_parameters = item.get("parameters").and_then(Value::as_array);
+            for (method, operation) in item.as_object().into_iter().flatten() {
+                let method = match method.as_str() {
+                    "get" => Method::GET,
+                    "put" => Method::PUT,
+                    "post" => Method::POST,
+                    "delete" => Method::DELETE,
+                    "patch" => Method::PATCH,
+                    _ => continue,
+                };
+
+                let schema = OperationSchema::compile(
+                    document,
+                    &components,
+                    path,
+                    method,
+                    shared_parameters,
+                    operation,
+                );
+                if schema.body.is_some() || !schema.query.is_empty() {
+                    operations.push(schema);
+                }
+            }
+        }
+
+        operations.sort_by_key(|operation| operation.parameter_count());
+        Self { operations }
+    }
+
+    pub fn find(&self, method: &Method, path: &str) -> Option<&OperationSchema> {
+        let segments: Vec<&str> = path.trim_end_matches('/').split('/').collect();
+        self.operations
+            .iter()
+            .find(|operation| &operation.method == method && operation.matches(&segments))
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Segment {
+    Literal(String),
+    Parameter,
+}
+
+pub struct OperationSchema {
+    method: Method,
+    segments: Vec<Segment>,
+    query: Vec<QueryParameter>,
+    body: Option<BodySchema>,
+}
+
+struct QueryParameter {
+    name: String,
+    required: bool,
+    kind: ValueKind,
+    schema: Option<JSONSchema>,
+}
+
+struct BodySchema {
+    required: bool,
+    schema: Option<JSONSchema>,
+}
+
+impl OperationSchema {
+    fn compile(
+        document: &Value,
+        components: &Value,
+        path: &str,
+        method: Method,
+        shared_parameters: Option<&Vec<Value>>,
+        operation: &Value,
+    ) -> Self {
+        let segments = path
+            .trim_end_matches('/')
+            .split('/')
+            .map(|segment| {
+                if segment.starts_with('{') && segment.ends_with('}') {
+                    Segment::Parameter
+                } else {
+                    Segment::Literal(segment.to_string())
+                }
+            })
+            .collect();
+
+        // The operation's own parameters replace the path item's of the same name
+        let mut parameters: Vec<&Value> = Vec::new();
+        let own_parameters = operation.get("parameters").and_then(Value::as_array);
+        for parameter in shared_parameters.into_iter().chain(own_parameters).flatten() {
+            let parameter = resolve(document, parameter);
+            if parameter.get("in").and_then(Value::as_str) != Some("query") {
+                continue;
+            }
+            parameters.retain(|existing| existing.get("name") != parameter.get("name"));
+            parameters.push(parameter);
+        }
+
+        let query = parameters
+            .into_iter()
+            .filter_map(|parameter| {
+                let name = parameter.get("name")?.as_str()?.to_string();
+                let schema = parameter.get("schema").cloned().unwrap_or_else(|| json!({}));
+                Some(QueryParameter {
+                    required: parameter.get("required").and_then(Value::as_bool).unwrap_or(false),
+                    kind: ValueKind::of(resolve(document, &schema)),
+                    schema: compile_schema(&schema, components, &method, path),
+                    name,
+                })
+            })
+            .collect();
+
+        let body = operation.get("requestBody").map(|body| resolve(document, body)).and_then(|body| {
+            let schema = body.pointer("/content/application~1json/schema")?;
+            Some(BodySchema {
+                required: body.get("required").and_then(Value::as_bool).unwrap_or(false),
+                schema: compile_schema(schema, components, &method, path),
+            })
+        });
+
+        Self {
+            method,
+            segments,
+            query,
+            body,
+        }
+    }
+
+    fn parameter_count(&self) -> usize {
+        self.segments.iter().filter(|segment| **segment == Segment::Parameter).count()
+    }
+
+    fn matches(&self, segments: &[&str]) -> bool {
+        self.segments.len() == segments.len()
+            && self.segments.iter().zip(segments).all(|(expected, actual)| match expected {
+                Segment::Literal(literal) => literal.as_str() == *actual,
+                Segment::Parameter => !actual.is_empty(),
+            })
+    }
+
+    /// Whether the request body has to be read to validate the request
+    pub fn has_body_schema(&self) -> bool {
+        self.body.is_some()
+    }
+
+    pub fn validate_query(&self, uri: &Uri, max_errors: usize) -> Vec<ValidationError> {
+        let pairs = Query::<Vec<(String, String)>>::try_from_uri(uri)
+            .map(|query| query.0)
+            .unwrap_or_default();
+        let mut errors = Vec::new();
+
+        for parameter in &self.query {
+            let field = format!("query.{}", parameter.name);
+            let values: Vec<&str> = pairs
+                .iter()
+                .filter(|(name, _)| *name == parameter.name)
+                .map(|(_, value)| value.as_str())
+                .collect();
+            if values.is_empty() {
+                if parameter.required {
+                    errors.push(missing(field, format!("Query parameter {} is required", parameter.name)));
+                }
+                continue;
+            }
+
+            if let Some(schema) = &parameter.schema {
+                let value = parameter.kind.coerce(&values);
+                errors.extend(schema_errors(&field, schema, &value, true, max_errors));
+            }
+        }
+
+        errors.truncate(max_errors);
+        errors
+    }
+
+    pub fn validate_body(&self, body: &[u8], max_errors: usize) -> Vec<ValidationError> {
+        let Some(expected) = &self.body else {
+            return Vec::new();
+        };
+        if body.iter().all(u8::is_ascii_whitespace) {
+            return if expected.required {
+                vec![missing("body".to_string(), "Request body is required".to_string())]
+            } else {
+                Vec::new()
+            };
+        }
+
+        let value: Value = match serde_json::from_slice(body) {
+            Ok(value) => value,
+            Err(e) => {
+                return vec![ValidationError {
+                    field: "body".to_string(),
+                    code: "invalid_json".to_string(),
+                    message: format!("Request body is not valid JSON: {}", e),
+                    rejected_value: None,
+                }]
+            }
+        };
+
+        // Bodies may carry credentials, so their values aren't echoed back
+        match &expected.schema {
+            Some(schema) => schema_errors("body", schema, &value, false, max_errors),
+            None => Vec::new(),
+        }
+    }
+}
+
+/// JSON type a query parameter's string values are read as
+#[derive(Debug, PartialEq)]
+enum ValueKind {
+    String,
+    Integer,
+    Number,
+    Boolean,
+    Array(Box<ValueKind>),
+}
+
+impl ValueKind {
+    fn of(schema: &Value) -> Self {
+        match schema.get("type").and_then(Value::as_str) {
+            Some("integer") => ValueKind::Integer,
+            Some("number") => ValueKind::Number,
+            Some("boolean") => ValueKind::Boolean,
+            Some("array") => ValueKind::Array(Box::new(
+                schema.get("items").map(ValueKind::of).unwrap_or(ValueKind::String),
+            )),
+            _ => ValueKind::String,
+        }
+    }
+
+    /// Repeated parameters and comma-separated values both make up arrays.
+    /// Values that don't parse stay strings, so the schema reports them.
+    fn coerce(&self, values: &[&str]) -> Value {
+        match self {
+            ValueKind::Array(item) => values
+                .iter()
+                .flat_map(|value| value.split(','))
+                .map(|value| item.coerce_one(value))
+                .collect(),
+            _ => self.coerce_one(values[0]),
+        }
+    }
+
+    fn coerce_one(&self, value: &str) -> Value {
+        let parsed = match self {
+            ValueKind::Integer => value.parse::<i64>().ok().map(Value::from),
+            ValueKind::Number => value.parse::<f64>().ok().and_then(serde_json::Number::from_f64).map(Value::Number),
+            ValueKind::Boolean => value.parse::<bool>().ok().map(Value::Bool),
+            ValueKind::String | ValueKind::Array(_) => None,
+        };
+        parsed.unwrap_or_else(|| Value::String(value.to_string()))
+    }
+}
+
+/// Follows a local `$ref`, e.g. to `#/components/parameters/...`
+fn resolve<'a>(document: &'a Value, value: &'a Value) -> &'a Value {
+    value
+        .get("$ref")
+        .and_then(Value::as_str)
+        .and_then(|reference| reference.strip_prefix('#'))
+        .and_then(|pointer| document.pointer(pointer))
+        .unwrap_or(value)
+}
+
+/// Schemas that don't compile aren't enforced, since a service's mistake
+/// in its document shouldn't take its endpoints down
+fn compile_schema(schema: &Value, components: &Value, method: &Method, path: &str) -> Option<JSONSchema> {
+    // The components go along so `#/components/schemas/...` references resolve
+    let mut root = json!({ "allOf": [schema], "components": components });
+    allow_nullable(&mut root);
+
+    match JSONSchema::options().with_draft(Draft::Draft7).compile(&root) {
+        Ok(compiled) => Some(compiled),
+        Err(e) => {
+            warn!(method = %method, path = %path, error = %e, "Request schema doesn't compile, not validating it");
+            None
+        }
+    }
+}
+
+/// OpenAPI 3.0's `nullable: true` as the JSON Schema type list it means
+fn allow_nullable(schema: &mut Value) {
+    match schema {
+        Value::Object(object) => {
+            if object.get("nullable") == Some(&Value::Bool(true)) {
+                if let Some(Value::String(kind)) = object.get("type").cloned() {
+                    object.insert("type".to_string(), json!([kind, "null"]));
+                }
+            }
+            object.values_mut().for_each(allow_nullable);
+        }
+        Value::Array(items) => items.iter_mut().for_each(allow_nullable),
+        _ => {}
+    }
+}
+
+fn schema_errors(
+    location: &str,
+    schema: &JSONSchema,
+    value: &Value,
+    echo_values: bool,
+    max_errors: usize,
+) -> Vec<ValidationError> {
+    let Err(errors) = schema.validate(value) else {
+        return Vec::new();
+    };
+
+    errors
+        .take(max_errors)
+        .map(|error| {
+            let mut path = error.instance_path.clone().into_vec();
+            // Missing properties are reported at the property, not its parent
+            let is_missing = if let ValidationErrorKind::Required { property } = &error.kind {
+                path.push(property.as_str().map(str::to_string).unwrap_or_else(|| property.to_string()));
+                true
+            } else {
+                false
+            };
+
+            ValidationError {
+                field: field_path(location, &path),
+                code: error.schema_path.clone().into_vec().pop().unwrap_or_else(|| "schema".to_string()),
+                message: error.to_string(),
+                rejected_value: (echo_values && !is_missing).then(|| error.instance.clone().into_owned()),
+            }
+        })
+        .collect()
+}
+
+fn missing(field: String, message: String) -> ValidationError {
+    ValidationError {
+        field,
+        code: "required".to_string(),
+        message,
+        rejected_value: None,
+    }
+}
+
+/// Dotted field path with array indexes, e.g. `body.items[0].name`
+fn field_path(location: &str, path: &[String]) -> String {
+    let mut field = location.to_string();
+    for segment in path {
+        if !segment.is_empty() && segment.bytes().all(|b| b.is_ascii_digit()) {
+            field.push_str(&format!("[{}]", segment));
+        } else {
+            field.push('.');
+            field.push_str(segment);
+        }
+    }
+    field
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn document() -> Value {
+        json!({
+            "openapi": "3.0.3",
+            "paths": {
+                "/api/v1/users": {
+                    "get": {
+                        "parameters": [
+                            { "name": "limit", "in": "query", "schema": { "type": "integer", "minimum": 1, "maximum": 100 } },
+                            { "name": "status", "in": "query", "schema": { "type": "array", "items": { "type": "string", "enum": ["active", "invited"] } } }
+                        ]
+                    },
+                    "post": {
+                        "requestBody": {
+                            "required": true,
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/CreateUser" } } }
+                        }
+                    }
+                },
+                "/api/v1/users/{id}": {
+                    "parameters": [
+                        { "$ref": "#/components/parameters/Fields" }
+                    ],
+                    "put": {
+                        "requestBody": {
+                            "content": { "application/json": { "schema": { "$ref": "#/components/schemas/CreateUser" } } }
+                        }
+                    }
+                },
+                "/api/v1/users/me": {
+                    "put": {
+                        "requestBody": {
+                            "content": { "application/json": { "schema": { "type": "object" } } }
+                        }
+                    }
+                }
+            },
+            "components": {
+                "parameters": {
+                    "Fields": { "name": "fields", "in": "query", "required": true, "schema": { "type": "string" } }
+                },
+                "schemas": {
+                    "CreateUser": {
+                        "type": "object",
+                        "required": ["email", "roles"],
+                        "properties": {
+                            "email": { "type": "string", "minLength": 3 },
+                            "display_name": { "type": "string", "nullable": true },
+                            "roles": { "type": "array", "items": { "type": "string" } }
+                        }
+                    }
+                }
+            }
+        })
+    }
+
+    fn uri(uri: &str) -> Uri {
+        uri.parse().unwrap()
+    }
+
+    #[test]
+    fn test_finds_operations_by_template() {
+        let schemas = OperationSchemas::compile(&document());
+
+        assert!(schemas.find(&Method::POST, "/api/v1/users").unwrap().has_body_schema());
+        assert!(schemas.find(&Method::PUT, "/api/v1/users/42").is_some());
+        assert!(schemas.find(&Method::DELETE, "/api/v1/users/42").is_none());
+        assert!(schemas.find(&Method::GET, "/api/v1/files").is_none());
+
+        // The literal path is preferred over the template
+        let me = schemas.find(&Method::PUT, "/api/v1/users/me").unwrap();
+        assert!(me.validate_query(&uri("/api/v1/users/me"), 20).is_empty());
+    }
+
+    #[test]
+    fn test_validates_body_with_field_paths() {
+        let schemas = OperationSchemas::compile(&document());
+        let create = schemas.find(&Method::POST, "/api/v1/users").unwrap();
+
+        assert!(create.validate_body(br#"{"email": "ada@example.com", "roles": [], "display_name": null}"#, 20).is_empty());
+
+        let errors = create.validate_body(br#"{"email": "ad", "roles": ["admin", 7]}"#, 20);
+        let mut fields: Vec<(&str, &str)> = errors.iter().map(|e| (e.field.as_str(), e.code.as_str())).collect();
+        fields.sort();
+        assert_eq!(fields, vec![("body.email", "minLength"), ("body.roles[1]", "type")]);
+        assert!(errors.iter().all(|e| e.rejected_value.is_none()));
+
+        let errors = create.validate_body(br#"{"email": "ada@example.com"}"#, 20);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "body.roles");
+        assert_eq!(errors[0].code, "required");
+
+        assert_eq!(create.validate_body(b"", 20)[0].code, "required");
+        assert_eq!(create.validate_body(b"{", 20)[0].code, "invalid_json");
+    }
+
+    #[test]
+    fn test_optional_body_may_be_empty() {
+        let schemas = OperationSchemas::compile(&document());
+        let update = schemas.find(&Method::PUT, "/api/v1/users/42").unwrap();
+
+        assert!(update.validate_body(b"", 20).is_empty());
+    }
+
+    #[test]
+    fn test_validates_query_parameters() {
+        let schemas = OperationSchemas::compile(&document());
+        let list = schemas.find(&Method::GET, "/api/v1/users").unwrap();
+
+        assert!(list.validate_query(&uri("/api/v1/users"), 20).is_empty());
+        assert!(list.validate_query(&uri("/api/v1/users?limit=10&status=active,invited"), 20).is_empty());
+
+        let errors = list.validate_query(&uri("/api/v1/users?limit=500&status=active&status=gone"), 20);
+        let fields: Vec<&str> = errors.iter().map(|e| e.field.as_str()).collect();
+        assert_eq!(fields, vec!["query.limit", "query.status[1]"]);
+        assert_eq!(errors[0].rejected_value, Some(json!(500)));
+
+        let errors = list.validate_query(&uri("/api/v1/users?limit=ten"), 20);
+        assert_eq!(errors[0].code, "type");
+        assert_eq!(errors[0].rejected_value, Some(json!("ten")));
+    }
+
+    #[test]
+    fn test_required_query_parameter_from_path_item() {
+        let schemas = OperationSchemas::compile(&document());
+        let update = schemas.find(&Method::PUT, "/api/v1/users/42").unwrap();
+
+        let errors = update.validate_query(&uri("/api/v1/users/42"), 20);
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].field, "query.fields");
+        assert!(update.validate_query(&uri("/api/v1/users/42?fields=email"), 20).is_empty());
+    }
+
+    #[test]
+    fn test_error_count_is_capped() {
+        let schemas = OperationSchemas::compile(&document());
+        let create = schemas.find(&Method::POST, "/api/v1/users").unwrap();
+
+        assert_eq!(create.validate_body(br#"{"roles": [1, 2, 3, 4]}"#, 2).len(), 2);
+    }
+
+    #[test]
+    fn test_field_paths() {
+        assert_eq!(field_path("body", &[]), "body");
+        assert_eq!(
+            field_path("body", &["items".to_string(), "0".to_string(), "name".to_string()]),
+            "body.items[0].name"
+        );
+    }
+}
@@ -0,0 +1,799 @@
+//! Inbound webhooks from third-party integrations.
+//!
+//! Each integration declared in the webhook file gets an endpoint at
+//! `/webhooks/{name}`. A delivery's signature is checked with the
+//! integration's verifier (Stripe, GitHub or a generic HMAC scheme), repeats
+//! of a delivery already handled are acknowledged without being handled
+//! again, and the payload is archived in Redis before it's dispatched to a
+//! Temporal workflow or published on the module event bus. Services get
+//! verified, deduplicated events and don't run webhook receivers of their own.
+//!
+//! ```json
+//! [
+//!   {
+//!     "name": "github",
+//!     "verifier": { "type": "github", "secret_env": "GITHUB_WEBHOOK_SECRET" },
+//!     "tenant_id_pointer": "/installation/account/login",
+//!     "dispatch": { "type": "event", "event_type": "platform.webhook.github" }
+//!   }
+//! ]
+//! ```
+
+use axum::http::HeaderMap;
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use redis::{AsyncCommands, Client as RedisClient};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+use crate::config::WebhookConfig;
+use crate::error::{ApiGatewayError, ApiResult};
+use crate::temporal_client::ApiGatewayTemporalClient;
+use adx_shared::telemetry::TracedRequestExt;
+
+/// Where integrations' endpoints live
+pub const WEBHOOK_PATH_PREFIX: &str = "/webhooks/";
+
+/// Tenant deliveries are dispatched under when the integration doesn't say
+/// which tenant they belong to
+pub const PLATFORM_TENANT: &str = "platform";
+
+/// Request headers that aren't archived with a payload
+const UNARCHIVED_HEADERS: &[&str] = &["authorization", "cookie", "proxy-authorization"];
+
+const DEFAULT_TOLERANCE_SECONDS: i64 = 300;
+
+fn default_tolerance() -> i64 {
+    DEFAULT_TOLERANCE_SECONDS
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct WebhookIntegration {
+    /// Path segment of the integration's endpoint
+    pub name: String,
+    pub verifier: Verifier,
+    /// JSON pointer to the tenant id in the payload
+    pub tenant_id_pointer: Option<String>,
+    /// Tenant of every delivery, when the payload doesn't carry one
+    pub tenant_id: Option<String>,
+    /// JSON pointer to the event type in the payload, in place of the
+    /// verifier's own, e.g. Stripe's `type`
+    pub event_type_pointer: Option<String>,
+    pub dispatch: Dispatch,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Verifier {
+    /// `Stripe-Signature` with a timestamp; the delivery id is the event id
+    Stripe {
+        secret_env: String,
+        #[serde(default = "default_tolerance")]
+        tolerance_seconds: i64,
+    },
+    /// `X-Hub-Signature-256`; the delivery id is `X-GitHub-Delivery`
+    Github { secret_env: String },
+    /// HMAC-SHA256 of the body, or of `{timestamp}.{body}` when the sender
+    /// includes a timestamp header
+    Hmac {
+        secret_env: String,
+        signature_header: String,
+        /// Stripped from the header value before decoding, e.g. `sha256=`
+        #[serde(default)]
+        signature_prefix: String,
+        #[serde(default)]
+        encoding: SignatureEncoding,
+        timestamp_header: Option<String>,
+        #[serde(default = "default_tolerance")]
+        tolerance_seconds: i64,
+        /// Header carrying the sender's delivery id; the payload's hash
+        /// stands in for it otherwise
+        id_header: Option<String>,
+    },
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SignatureEncoding {
+    #[default]
+    Hex,
+    Base64,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Dispatch {
+    /// Start a workflow per delivery, with an id derived from the delivery id
+    Workflow { workflow_type: String, task_queue: String },
+    /// Publish a platform event on module-service's event bus
+    Event { event_type: String },
+}
+
+impl Verifier {
+    fn secret_env(&self) -> &str {
+        match self {
+            Verifier::Stripe { secret_env, .. }
+            | Verifier::Github { secret_env }
+            | Verifier::Hmac { secret_env, .. } => secret_env,
+        }
+    }
+
+    /// Checks the delivery's signature and that it isn't older than the
+    /// tolerance, where the scheme signs a timestamp
+    pub fn verify(&self, secret: &[u8], headers: &HeaderMap, body: &[u8], now: i64) -> Result<(), String> {
+        match self {
+            Verifier::Stripe { tolerance_seconds, .. } => {
+                let header = header_value(headers, "Stripe-Signature").ok_or("Missing Stripe-Signature header")?;
+                let mut timestamp = None;
+                let mut signatures = Vec::new();
+                for part in header.split(',') {
+                    match part.trim().split_once('=') {
+                        Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+                        Some(("v1", value)) => signatures.push(value),
+                        _ => {}
+                    }
+                }
+                let timestamp = timestamp.ok_or("Stripe signature has no timestamp")?;
+                check_tolerance(timestamp, now, *tolerance_seconds)?;
+
+                let signed = signed_with_timestamp(&timestamp.to_string(), body);
+                if signatures.iter().any(|signature| signature_matches(secret, &signed, signature, SignatureEncoding::Hex)) {
+                    Ok(())
+                } else {
+                    Err("Stripe signature does not match".to_string())
+                }
+            }
+            Verifier::Github { .. } => {
+                let header = header_value(headers, "X-Hub-Signature-256").ok_or("Missing X-Hub-Signature-256 header")?;
+                let signature = header.strip_prefix("sha256=").ok_or("Signature must start with sha256=")?;
+                if signature_matches(secret, body, signature, SignatureEncoding::Hex) {
+                    Ok(())
+                } else {
+                    Err("GitHub signature does not match".to_string())
+                }
+            }
+            Verifier::Hmac {
+                signature_header,
+                signature_prefix,
+                encoding,
+                timestamp_header,
+                tolerance_seconds,
+                ..
+            } => {
+                let header = header_value(headers, signature_header)
+                    .ok_or_else(|| format!("Missing {} header", signature_header))?;
+                let signature = header.strip_prefix(signature_prefix.as_str()).unwrap_or(header);
+
+                let signed = match timestamp_header {
+                    Some(timestamp_header) => {
+                        let timestamp = header_value(headers, timestamp_header)
+                            .ok_or_else(|| format!("Missing {} header", timestamp_header))?;
+                        let seconds = timestamp
+                            .parse::<i64>()
+                            .map_err(|_| format!("{} must be a Unix timestamp", timestamp_header))?;
+                        check_tolerance(seconds, now, *tolerance_seconds)?;
+                        signed_with_timestamp(timestamp, body)
+                    }
+                    None => body.to_vec(),
+                };
+                if signature_matches(secret, &signed, signature, *encoding) {
+                    Ok(())
+                } else {
+                    Err(format!("{} does not match", signature_header))
+                }
+            }
+        }
+    }
+
+    /// The sender's id for the delivery, which repeats of it share
+    pub fn delivery_id(&self, headers: &HeaderMap, payload: Option<&Value>, body: &[u8]) -> String {
+        let id = match self {
+            Verifier::Stripe { .. } => payload.and_then(|p| p.get("id")).and_then(Value::as_str).map(str::to_string),
+            Verifier::Github { .. } => header_value(headers, "X-GitHub-Delivery").map(str::to_string),
+            Verifier::Hmac { id_header, .. } => id_header
+                .as_deref()
+                .and_then(|id_header| header_value(headers, id_header))
+                .map(str::to_string),
+        };
+        id.filter(|id| !id.is_empty() && id.len() <= 255 && id.chars().all(|c| c.is_ascii_graphic()))
+            .unwrap_or_else(|| format!("sha256-{:x}", Sha256::digest(body)))
+    }
+
+    fn event_type(&self, headers: &HeaderMap, payload: Option<&Value>) -> Option<String> {
+        match self {
+            Verifier::Stripe { .. } => payload.and_then(|p| p.get("type")).and_then(Value::as_str).map(str::to_string),
+            Verifier::Github { .. } => header_value(headers, "X-GitHub-Event").map(str::to_string),
+            Verifier::Hmac { .. } => None,
+        }
+    }
+}
+
+impl WebhookIntegration {
+    pub fn tenant_id(&self, payload: Option<&Value>) -> String {
+        self.tenant_id_pointer
+            .as_deref()
+            .and_then(|pointer| payload?.pointer(pointer))
+            .and_then(|value| match value {
+                Value::String(tenant_id) => Some(tenant_id.clone()),
+                Value::Number(tenant_id) => Some(tenant_id.to_string()),
+                _ => None,
+            })
+            .or_else(|| self.tenant_id.clone())
+            .unwrap_or_else(|| PLATFORM_TENANT.to_string())
+    }
+
+    pub fn event_type(&self, headers: &HeaderMap, payload: Option<&Value>) -> Option<String> {
+        match &self.event_type_pointer {
+            Some(pointer) => payload
+                .and_then(|payload| payload.pointer(pointer))
+                .and_then(Value::as_str)
+                .map(str::to_string),
+            None => self.verifier.event_type(headers, payload),
+        }
+    }
+}
+
+fn header_value<'a>(headers: &'a HeaderMap, name: &str) -> Option<&'a str> {
+    headers.get(name).and_then(|value| value.to_str().ok()).map(str::trim)
+}
+
+fn check_tolerance(timestamp: i64, now: i64, tolerance_seconds: i64) -> Result<(), String> {
+    if (now - timestamp).abs() > tolerance_seconds {
+        Err("Webhook timestamp is outside the tolerance".to_string())
+    } else {
+        Ok(())
+    }
+}
+
+fn signed_with_timestamp(timestamp: &str, body: &[u8]) -> Vec<u8> {
+    let mut signed = Vec::with_capacity(timestamp.len() + 1 + body.len());
+    signed.extend_from_slice(timestamp.as_bytes());
+    signed.push(b'.');
+    signed.extend_from_slice(body);
+    signed
+}
+
+/// Constant-time comparison of an encoded HMAC-SHA256 signature
+fn signature_matches(secret: &[u8], signed: &[u8], signature: &str, encoding: SignatureEncoding) -> bool {
+    let decoded = match encoding {
+        SignatureEncoding::Hex => hex::decode(signature.trim()).ok(),
+        SignatureEncoding::Base64 => base64::engine::general_purpose::STANDARD.decode(signature.trim()).ok(),
+    };
+    let Some(decoded) = decoded else {
+        return false;
+    };
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret).expect("HMAC accepts keys of any length");
+    mac.update(signed);
+    mac.verify_slice(&decoded).is_ok()
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Dispatched,
+    /// Dispatch failed; the sender's retry or a replay dispatches it again
+    Failed,
+}
+
+/// A delivery as received, kept for inspection and replay
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedDelivery {
+    pub integration: String,
+    pub delivery_id: String,
+    pub event_type: Option<String>,
+    pub tenant_id: String,
+    pub received_at: DateTime<Utc>,
+    pub status: DeliveryStatus,
+    pub error: Option<String>,
+    /// Workflow started for the delivery
+    pub workflow_id: Option<String>,
+    pub headers: Vec<(String, String)>,
+    /// Base64, since payloads needn't be UTF-8
+    pub body: String,
+}
+
+impl ArchivedDelivery {
+    fn payload(&self) -> Value {
+        let body = base64::engine::general_purpose::STANDARD
+            .decode(&self.body)
+            .unwrap_or_default();
+        parse_payload(&body).unwrap_or_else(|| Value::String(String::from_utf8_lossy(&body).into_owned()))
+    }
+}
+
+/// What the sender is told about its delivery
+#[derive(Debug, Clone, Serialize)]
+pub struct WebhookReceipt {
+    pub integration: String,
+    pub delivery_id: String,
+    /// The delivery was handled before and wasn't dispatched again
+    pub duplicate: bool,
+    pub workflow_id: Option<String>,
+}
+
+fn parse_payload(body: &[u8]) -> Option<Value> {
+    serde_json::from_slice(body).ok()
+}
+
+/// Verifies, deduplicates, archives and dispatches deliveries
+pub struct WebhookReceiver {
+    integrations: HashMap<String, (WebhookIntegration, Vec<u8>)>,
+    redis_client: Arc<RedisClient>,
+    config: WebhookConfig,
+    temporal_client: Arc<ApiGatewayTemporalClient>,
+    http_client: reqwest::Client,
+    module_service_url: String,
+}
+
+impl WebhookReceiver {
+    pub fn from_file(
+        path: &str,
+        redis_url: &str,
+        config: WebhookConfig,
+        temporal_client: Arc<ApiGatewayTemporalClient>,
+        http_client: reqwest::Client,
+        module_service_url: &str,
+    ) -> ApiResult<Self> {
+        let contents = std::fs::read_to_string(path).map_err(|e| ApiGatewayError::ConfigurationError {
+            message: format!("Failed to read webhook integrations from {}: {}", path, e),
+        })?;
+        let integrations: Vec<WebhookIntegration> =
+            serde_json::from_str(&contents).map_err(|e| ApiGatewayError::ConfigurationError {
+                message: format!("Invalid webhook integrations in {}: {}", path, e),
+            })?;
+
+        let mut by_name = HashMap::new();
+        for integration in integrations {
+            let secret = std::env::var(integration.verifier.secret_env()).map_err(|_| {
+                ApiGatewayError::ConfigurationError {
+                    message: format!(
+                        "Webhook integration {} needs its secret in {}",
+                        integration.name,
+                        integration.verifier.secret_env()
+                    ),
+                }
+            })?;
+            if integration.name.is_empty() || integration.name.contains('/') {
+                return Err(ApiGatewayError::ConfigurationError {
+                    message: format!("Invalid webhook integration name {:?}", integration.name),
+                });
+            }
+            if by_name.contains_key(&integration.name) {
+                return Err(ApiGatewayError::ConfigurationError {
+                    message: format!("Webhook integration {} is declared twice", integration.name),
+                });
+            }
+            by_name.insert(integration.name.clone(), (integration, secret.into_bytes()));
+        }
+
+        let redis_client = RedisClient::open(redis_url).map_err(|e| ApiGatewayError::RedisError {
+            message: format!("Failed to create Redis client: {}", e),
+        })?;
+
+        Ok(Self {
+            integrations: by_name,
+            redis_client: Arc::new(redis_client),
+            config,
+            temporal_client,
+            http_client,
+            module_service_url: module_service_url.trim_end_matches('/').to_string(),
+        })
+    }
+
+    fn integration(&self, name: &str) -> ApiResult<&(WebhookIntegration, Vec<u8>)> {
+        self.integrations
+            .get(name)
+            .ok_or_else(|| ApiGatewayError::WebhookIntegrationNotFound {
+                integration: name.to_string(),
+            })
+    }
+
+    /// Handles one delivery to an integration's endpoint
+    pub async fn receive(&self, name: &str, headers: &HeaderMap, body: &[u8]) -> ApiResult<WebhookReceipt> {
+        let (integration, secret) = self.integration(name)?;
+        if body.len() > self.config.max_body_bytes {
+            return Err(ApiGatewayError::InvalidRequest {
+                message: format!("Webhook payload is larger than {} bytes", self.config.max_body_bytes),
+            });
+        }
+
+        integration
+            .verifier
+            .verify(secret, headers, body, Utc::now().timestamp())
+            .map_err(|reason| {
+                warn!(integration = %name, reason = %reason, "Refusing webhook delivery");
+                ApiGatewayError::WebhookRejected {
+                    integration: name.to_string(),
+                    reason,
+                }
+            })?;
+
+        let payload = parse_payload(body);
+        let delivery_id = integration.verifier.delivery_id(headers, payload.as_ref(), body);
+
+        if !self.claim(name, &delivery_id).await? {
+            info!(integration = %name, delivery_id = %delivery_id, "Acknowledging repeated webhook delivery");
+            return Ok(WebhookReceipt {
+                integration: name.to_string(),
+                delivery_id,
+                duplicate: true,
+                workflow_id: None,
+            });
+        }
+
+        let mut delivery = ArchivedDelivery {
+            integration: name.to_string(),
+            delivery_id: delivery_id.clone(),
+            event_type: integration.event_type(headers, payload.as_ref()),
+            tenant_id: integration.tenant_id(payload.as_ref()),
+            received_at: Utc::now(),
+            status: DeliveryStatus::Failed,
+            error: None,
+            workflow_id: None,
+            headers: headers
+                .iter()
+                .filter(|(name, _)| !UNARCHIVED_HEADERS.contains(&name.as_str()))
+                .filter_map(|(name, value)| Some((name.as_str().to_string(), value.to_str().ok()?.to_string())))
+                .collect(),
+            body: base64::engine::general_purpose::STANDARD.encode(body),
+        };
+
+        let result = self.dispatch(integration, &mut delivery).await;
+        match &result {
+            Ok(()) => self.mark_handled(name, &delivery_id).await,
+            // The sender's retry dispatches it again
+            Err(e) => {
+                delivery.error = Some(e.to_string());
+                self.release(name, &delivery_id).await;
+            }
+        }
+        if let Err(e) = self.archive(&delivery).await {
+            warn!(integration = %name, delivery_id = %delivery_id, error = %e, "Failed to archive webhook delivery");
+        }
+        result?;
+
+        Ok(WebhookReceipt {
+            integration: name.to_string(),
+            delivery_id,
+            duplicate: false,
+            workflow_id: delivery.workflow_id,
+        })
+    }
+
+    /// Dispatches an archived delivery again, e.g. after fixing the
+    /// workflow or subscriber that failed it
+    pub async fn replay(&self, name: &str, delivery_id: &str) -> ApiResult<ArchivedDelivery> {
+        let (integration, _) = self.integration(name)?;
+        let mut delivery = self.get(name, delivery_id).await?.ok_or_else(|| ApiGatewayError::InvalidRequest {
+            message: format!("No archived delivery {} for webhook integration {}", delivery_id, name),
+        })?;
+
+        delivery.error = None;
+        let result = self.dispatch(integration, &mut delivery).await;
+        if let Err(e) = &result {
+            delivery.status = DeliveryStatus::Failed;
+            delivery.error = Some(e.to_string());
+        }
+        self.archive(&delivery).await?;
+        result?;
+
+        info!(integration = %name, delivery_id = %delivery_id, "Webhook delivery replayed");
+        Ok(delivery)
+    }
+
+    async fn dispatch(&self, integration: &WebhookIntegration, delivery: &mut ArchivedDelivery) -> ApiResult<()> {
+        let input = serde_json::json!({
+            "integration": delivery.integration,
+            "delivery_id": delivery.delivery_id,
+            "event_type": delivery.event_type,
+            "tenant_id": delivery.tenant_id,
+            "received_at": delivery.received_at,
+            "payload": delivery.payload(),
+        });
+
+        match &integration.dispatch {
+            Dispatch::Workflow {
+                workflow_type,
+                task_queue,
+            } => {
+                // Replays start a workflow of their own; a delivery's first
+                // dispatch reuses the id if the sender retries
+                let workflow_id = match delivery.status {
+                    DeliveryStatus::Dispatched => format!(
+                        "{}-webhook-{}-{}-replay-{}",
+                        workflow_type,
+                        delivery.integration,
+                        delivery.delivery_id,
+                        Utc::now().timestamp_millis()
+                    ),
+                    DeliveryStatus::Failed => {
+                        format!("{}-webhook-{}-{}", workflow_type, delivery.integration, delivery.delivery_id)
+                    }
+                };
+                let user_id = format!("webhook:{}", delivery.integration);
+                self.temporal_client
+                    .start_workflow(
+                        workflow_type,
+                        Some(workflow_id.clone()),
+                        task_queue,
+                        input,
+                        &delivery.tenant_id,
+                        &user_id,
+                    )
+                    .await?;
+                delivery.workflow_id = Some(workflow_id);
+            }
+            Dispatch::Event { event_type } => {
+                let response = self
+                    .http_client
+                    .post(format!("{}/api/v1/events/platform", self.module_service_url))
+                    .with_trace_context()
+                    .json(&serde_json::json!({
+                        "tenant_id": delivery.tenant_id,
+                        "event_type": event_type,
+                        "data": input,
+                    }))
+                    .send()
+                    .await
+                    .map_err(|_| ApiGatewayError::ServiceUnavailable {
+                        service: "module".to_string(),
+                    })?;
+                if !response.status().is_success() {
+                    return Err(ApiGatewayError::ServiceUnavailable {
+                        service: "module".to_string(),
+                    });
+                }
+            }
+        }
+
+        delivery.status = DeliveryStatus::Dispatched;
+        Ok(())
+    }
+
+    /// Most recent archived deliveries of an integration
+    pub async fn list(&self, name: &str, limit: usize) -> ApiResult<Vec<ArchivedDelivery>> {
+        self.integration(name)?;
+        let mut conn = self.redis_client.get_async_connection().await?;
+        let ids: Vec<String> = conn
+            .zrevrange(index_key(name), 0, limit.saturating_sub(1) as isize)
+            .await?;
+        if ids.is_empty() {
+            return Ok(Vec::new());
+        }
+
+        let keys: Vec<String> = ids.iter().map(|id| archive_key(name, id)).collect();
+        let stored: Vec<Option<String>> = redis::cmd("MGET").arg(&keys).query_async(&mut conn).await?;
+        Ok(stored
+            .into_iter()
+            .flatten()
+            .filter_map(|stored| serde_json::from_str(&stored).ok())
+            .collect())
+    }
+
+    pub async fn get(&self, name: &str, delivery_id: &str) -> ApiResult<Option<ArchivedDelivery>> {
+        let mut conn = self.redis_client.get_async_connection().await?;
+        let stored: Option<String> = conn.get(archive_key(name, delivery_id)).await?;
+        Ok(stored.and_then(|stored| serde_json::from_str(&stored).ok()))
+    }
+
+    /// Claims a delivery for handling; false when it was handled before.
+    /// A delivery another request is still handling is refused, so its
+    /// sender retries rather than having it dropped if that fails.
+    async fn claim(&self, name: &str, delivery_id: &str) -> ApiResult<bool> {
+        let mut conn = self.redis_client.get_async_connection().await?;
+        let key = seen_key(name, delivery_id);
+        let claimed: Option<String> = redis::cmd("SET")
+            .arg(&key)
+            .arg("processing")
+            .arg("NX")
+            .arg("EX")
+            .arg(self.config.lock_seconds)
+            .query_async(&mut conn)
+            .await?;
+        if claimed.is_some() {
+            return Ok(true);
+        }
+
+        let state: Option<String> = conn.get(&key).await?;
+        match state.as_deref() {
+            Some("handled") => Ok(false),
+            _ => Err(ApiGatewayError::WebhookInProgress {
+                integration: name.to_string(),
+                delivery_id: delivery_id.to_string(),
+            }),
+        }
+    }
+
+    async fn mark_handled(&self, name: &str, delivery_id: &str) {
+        let result = async {
+            let mut conn = self.redis_client.get_async_connection().await?;
+            conn.set_ex::<_, _, ()>(seen_key(name, delivery_id), "handled", self.config.dedupe_seconds)
+                .await
+        }
+        .await;
+        if let Err(e) = result {
+            warn!(integration = %name, delivery_id = %delivery_id, error = %e, "Failed to record handled webhook delivery");
+        }
+    }
+
+    async fn release(&self, name: &str, delivery_id: &str) {
+        let result = async {
+            let mut conn = self.redis_client.get_async_connection().await?;
+            conn.del::<_, ()>(seen_key(name, delivery_id)).await
+        }
+        .await;
+        if let Err(e) = result {
+            warn!(integration = %name, delivery_id = %delivery_id, error = %e, "Failed to release webhook delivery; it expires with its lock");
+        }
+    }
+
+    async fn archive(&self, delivery: &ArchivedDelivery) -> ApiResult<()> {
+        let value = serde_json::to_string(delivery).map_err(|e| ApiGatewayError::InternalError {
+            message: format!("Failed to serialize webhook delivery: {}", e),
+        })?;
+        let index = index_key(&delivery.integration);
+        let cutoff = Utc::now().timestamp() - self.config.archive_seconds as i64;
+
+        let mut conn = self.redis_client.get_async_connection().await?;
+        redis::pipe()
+            .set_ex(archive_key(&delivery.integration, &delivery.delivery_id), value, self.config.archive_seconds)
+            .ignore()
+            .zadd(&index, &delivery.delivery_id, delivery.received_at.timestamp())
+            .ignore()
+            .zrembyscore(&index, "-inf", cutoff)
+            .ignore()
+            .query_async::<_, ()>(&mut conn)
+            .await?;
+        Ok(())
+    }
+}
+
+fn seen_key(integration: &str, delivery_id: &str) -> String {
+    format!("webhooks:seen:{}:{}", integration, delivery_id)
+}
+
+fn archive_key(integration: &str, delivery_id: &str) -> String {
+    format!("webhooks:archive:{}:{}", integration, delivery_id)
+}
+
+fn index_key(integration: &str) -> String {
+    format!("webhooks:archive:{}", integration)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+
+    const SECRET: &[u8] = b"whsec_test";
+
+    fn sign(signed: &[u8]) -> Vec<u8> {
+        let mut mac = Hmac::<Sha256>::new_from_slice(SECRET).unwrap();
+        mac.update(signed);
+        mac.finalize().into_bytes().to_vec()
+    }
+
+    fn headers(pairs: &[(&'static str, String)]) -> HeaderMap {
+        let mut headers = HeaderMap::new();
+        for (name, value) in pairs {
+            headers.insert(*name, HeaderValue::from_str(value).unwrap());
+        }
+        headers
+    }
+
+    fn hmac_verifier(timestamp_header: Option<&str>) -> Verifier {
+        Verifier::Hmac {
+            secret_env: "TEST_SECRET".to_string(),
+            signature_header: "X-Signature".to_string(),
+            signature_prefix: "v1=".to_string(),
+            encoding: SignatureEncoding::Base64,
+            timestamp_header: timestamp_header.map(str::to_string),
+            tolerance_seconds: 300,
+            id_header: Some("X-Delivery-Id".to_string()),
+        }
+    }
+
+    #[test]
+    fn test_stripe_signature_and_tolerance() {
+        let verifier = Verifier::Stripe {
+            secret_env: "STRIPE_WEBHOOK_SECRET".to_string(),
+            tolerance_seconds: 300,
+        };
+        let body = br#"{"id":"evt_1","type":"invoice.paid"}"#;
+        let signature = hex::encode(sign(&signed_with_timestamp("1700000000", body)));
+        let signed = headers(&[("Stripe-Signature", format!("t=1700000000,v1={}", signature))]);
+
+        assert!(verifier.verify(SECRET, &signed, body, 1_700_000_100).is_ok());
+        assert!(verifier.verify(SECRET, &signed, body, 1_700_000_400).is_err());
+        assert!(verifier.verify(SECRET, &signed, b"{}", 1_700_000_100).is_err());
+        assert!(verifier.verify(b"other", &signed, body, 1_700_000_100).is_err());
+        assert!(verifier.verify(SECRET, &HeaderMap::new(), body, 1_700_000_100).is_err());
+
+        let payload = parse_payload(body);
+        assert_eq!(verifier.delivery_id(&signed, payload.as_ref(), body), "evt_1");
+        assert_eq!(verifier.event_type(&signed, payload.as_ref()).as_deref(), Some("invoice.paid"));
+    }
+
+    #[test]
+    fn test_github_signature() {
+        let verifier = Verifier::Github {
+            secret_env: "GITHUB_WEBHOOK_SECRET".to_string(),
+        };
+        let body = br#"{"action":"opened"}"#;
+        let signed = headers(&[
+            ("X-Hub-Signature-256", format!("sha256={}", hex::encode(sign(body)))),
+            ("X-GitHub-Delivery", "72d3162e-cc78-11e3-81ab-4c9367dc0958".to_string()),
+            ("X-GitHub-Event", "pull_request".to_string()),
+        ]);
+
+        assert!(verifier.verify(SECRET, &signed, body, 0).is_ok());
+        assert!(verifier.verify(SECRET, &signed, b"{}", 0).is_err());
+        assert_eq!(verifier.delivery_id(&signed, None, body), "72d3162e-cc78-11e3-81ab-4c9367dc0958");
+        assert_eq!(verifier.event_type(&signed, None).as_deref(), Some("pull_request"));
+    }
+
+    #[test]
+    fn test_custom_hmac_signature() {
+        let body = b"plain text payload";
+        let engine = base64::engine::general_purpose::STANDARD;
+
+        let verifier = hmac_verifier(None);
+        let signed = headers(&[("X-Signature", format!("v1={}", engine.encode(sign(body))))]);
+        assert!(verifier.verify(SECRET, &signed, body, 0).is_ok());
+        assert!(verifier.verify(SECRET, &signed, b"tampered", 0).is_err());
+
+        let verifier = hmac_verifier(Some("X-Timestamp"));
+        let signed = headers(&[
+            ("X-Signature", engine.encode(sign(&signed_with_timestamp("1000", body)))),
+            ("X-Timestamp", "1000".to_string()),
+        ]);
+        assert!(verifier.verify(SECRET, &signed, body, 1200).is_ok());
+        assert!(verifier.verify(SECRET, &signed, body, 2000).is_err());
+    }
+
+    #[test]
+    fn test_delivery_id_falls_back_to_payload_hash() {
+        let verifier = hmac_verifier(None);
+        let id = verifier.delivery_id(&HeaderMap::new(), None, b"payload");
+        assert!(id.starts_with("sha256-"));
+        assert_eq!(id, verifier.delivery_id(&HeaderMap::new(), None, b"payload"));
+        assert_ne!(id, verifier.delivery_id(&HeaderMap::new(), None, b"other"));
+
+        let with_id = headers(&[("X-Delivery-Id", "d-42".to_string())]);
+        assert_eq!(verifier.delivery_id(&with_id, None, b"payload"), "d-42");
+    }
+
+    #[test]
+    fn test_integration_tenant_and_event_type() {
+        let integrations: Vec<WebhookIntegration> = serde_json::from_value(serde_json::json!([
+            {
+                "name": "crm",
+                "verifier": { "type": "hmac", "secret_env": "CRM_SECRET", "signature_header": "X-Signature" },
+                "tenant_id_pointer": "/account/tenant",
+                "tenant_id": "tenant-default",
+                "event_type_pointer": "/event",
+                "dispatch": { "type": "workflow", "workflow_type": "crm_sync", "task_queue": "crm-task-queue" }
+            }
+        ]))
+        .unwrap();
+        let integration = &integrations[0];
+
+        let payload = serde_json::json!({ "account": { "tenant": "tenant-1" }, "event": "contact.updated" });
+        assert_eq!(integration.tenant_id(Some(&payload)), "tenant-1");
+        assert_eq!(integration.tenant_id(Some(&serde_json::json!({}))), "tenant-default");
+        assert_eq!(
+            integration.event_type(&HeaderMap::new(), Some(&payload)).as_deref(),
+            Some("contact.updated")
+        );
+        assert!(matches!(integration.verifier, Verifier::Hmac { encoding: SignatureEncoding::Hex, .. }));
+
+        let mut unscoped = integration.clone();
+        unscoped.tenant_id = None;
+        assert_eq!(unscoped.tenant_id(None), PLATFORM_TENANT);
+    }
+}
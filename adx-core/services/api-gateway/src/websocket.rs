@@ -0,0 +1,608 @@
+//! WebSocket subscriptions.
+//!
+//! One authenticated connection carries any number of subscriptions, each
+//! routed by topic: workflow progress is watched by the gateway, and other
+//! topics relay a service's server-sent event stream scoped to the caller's
+//! tenant. Every event carries a cursor; a client that reconnects passes
+//! the last cursor it saw when subscribing again and resumes after it.
+
+use axum::extract::ws::{Message, WebSocket};
+use axum::http::{header, HeaderMap, Uri};
+use futures::{SinkExt, StreamExt};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use tokio::task::JoinHandle;
+use tracing::{debug, warn};
+
+use adx_shared::context_token::TENANT_CONTEXT_HEADER;
+use crate::config::WebSocketConfig;
+use crate::error::{ApiGatewayError, ApiResult};
+use crate::middleware::RequestContext;
+use crate::routing::{IntelligentRouter, SubscriptionSource};
+use crate::temporal_client::{ApiGatewayTemporalClient, WorkflowStatus};
+
+/// Where clients open their connection
+pub const WEBSOCKET_PATH: &str = "/api/v1/ws";
+
+/// Largest message a client may send
+pub const MAX_CLIENT_MESSAGE_BYTES: usize = 64 * 1024;
+
+/// Messages waiting for a slow client before subscriptions wait for it
+const OUTGOING_BUFFER: usize = 256;
+
+/// Times a lost event stream is reconnected in a row before giving up
+const MAX_STREAM_RECONNECTS: u32 = 5;
+
+#[derive(Debug, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ClientMessage {
+    Subscribe {
+        /// Chosen by the client; names the subscription in later messages
+        id: String,
+        topic: String,
+        #[serde(default)]
+        params: HashMap<String, String>,
+        /// Last cursor the client saw, to resume after it
+        cursor: Option<String>,
+    },
+    Unsubscribe {
+        id: String,
+    },
+    Ping,
+}
+
+#[derive(Debug, Serialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum ServerMessage {
+    Subscribed {
+        id: String,
+    },
+    Event {
+        id: String,
+        cursor: String,
+        event: String,
+        data: Value,
+    },
+    /// Nothing more will come for the subscription
+    Complete {
+        id: String,
+    },
+    Error {
+        #[serde(skip_serializing_if = "Option::is_none")]
+        id: Option<String>,
+        message: String,
+    },
+    Pong,
+}
+
+/// The subscriber, and how to reach the services as them
+pub struct Session {
+    router: Arc<IntelligentRouter>,
+    stream_client: reqwest::Client,
+    temporal_client: Arc<ApiGatewayTemporalClient>,
+    config: WebSocketConfig,
+    tenant_id: String,
+    headers: Vec<(String, String)>,
+}
+
+impl Session {
+    /// Service calls carry the caller's token, tenant and request id. None
+    /// without a tenant to scope subscriptions to.
+    pub fn new(
+        router: Arc<IntelligentRouter>,
+        stream_client: reqwest::Client,
+        temporal_client: Arc<ApiGatewayTemporalClient>,
+        config: WebSocketConfig,
+        request_headers: &HeaderMap,
+        uri: &Uri,
+        context: &RequestContext,
+    ) -> Option<Self> {
+        let tenant_id = context.tenant_context.as_ref()?.tenant_id.clone();
+
+        let mut headers = Vec::new();
+        let authorization = request_headers
+            .get(header::AUTHORIZATION)
+            .and_then(|value| value.to_str().ok())
+            .map(|value| value.to_string())
+            .or_else(|| query_token(uri).map(|token| format!("Bearer {}", token)));
+        if let Some(authorization) = authorization {
+            headers.push(("Authorization".to_string(), authorization));
+        }
+        if let Some(context_token) = request_headers
+            .get(TENANT_CONTEXT_HEADER)
+            .and_then(|value| value.to_str().ok())
+        {
+            headers.push((TENANT_CONTEXT_HEADER.to_string(), context_token.to_string()));
+        }
+        headers.push(("X-Request-ID".to_string(), context.request_id.clone()));
+        headers.push(("X-Tenant-ID".to_string(), tenant_id.clone()));
+
+        Some(Self {
+            router,
+            stream_client,
+            temporal_client,
+            config,
+            tenant_id,
+            headers,
+        })
+    }
+}
+
+/// Browsers can't set headers on WebSocket upgrades, so the token may come
+/// in the `access_token` query parameter of the WebSocket path instead
+pub fn query_token(uri: &Uri) -> Option<&str> {
+    if uri.path() != WEBSOCKET_PATH {
+        return None;
+    }
+    uri.query()?
+        .split('&')
+        .find_map(|pair| pair.strip_prefix("access_token="))
+        .filter(|token| !token.is_empty())
+}
+
+/// Serve one connection until the client goes away
+pub async fn serve(socket: WebSocket, session: Session) {
+    let session = Arc::new(session);
+    let (mut sink, mut incoming) = socket.split();
+    let (outgoing, mut outgoing_rx) = mpsc::channel::<ServerMessage>(OUTGOING_BUFFER);
+
+    let heartbeat = Duration::from_secs(session.config.heartbeat_seconds.max(1));
+    let writer = tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(heartbeat);
+        ticker.tick().await;
+        loop {
+            tokio::select! {
+                message = outgoing_rx.recv() => {
+                    let Some(message) = message else { break };
+                    let Ok(text) = serde_json::to_string(&message) else { continue };
+                    if sink.send(Message::Text(text)).await.is_err() {
+                        break;
+                    }
+                }
+                _ = ticker.tick() => {
+                    if sink.send(Message::Ping(Vec::new())).await.is_err() {
+                        break;
+                    }
+                }
+            }
+        }
+    });
+
+    let mut subscriptions: HashMap<String, JoinHandle<()>> = HashMap::new();
+    while let Some(Ok(message)) = incoming.next().await {
+        let text = match message {
+            Message::Text(text) => text,
+            Message::Close(_) => break,
+            _ => continue,
+        };
+
+        let message = match serde_json::from_str::<ClientMessage>(&text) {
+            Ok(message) => message,
+            Err(e) => {
+                let _ = outgoing.send(error(None, format!("Invalid message: {}", e))).await;
+                continue;
+            }
+        };
+
+        match message {
+            ClientMessage::Ping => {
+                let _ = outgoing.send(ServerMessage::Pong).await;
+            }
+            ClientMessage::Unsubscribe { id } => {
+                if let Some(task) = subscriptions.remove(&id) {
+                    task.abort();
+                }
+                let _ = outgoing.send(ServerMessage::Complete { id }).await;
+            }
+            ClientMessage::Subscribe { id, topic, params, cursor } => {
+                subscriptions.retain(|_, task| !task.is_finished());
+                if subscriptions.contains_key(&id) {
+                    let _ = outgoing.send(error(Some(id), "Subscription id is already in use".to_string())).await;
+                    continue;
+                }
+                if subscriptions.len() >= session.config.max_subscriptions {
+                    let _ = outgoing.send(error(Some(id), "Too many subscriptions on this connection".to_string())).await;
+                    continue;
+                }
+
+                let source = match session.router.get_subscription_route(&topic) {
+                    Ok(source) => source,
+                    Err(e) => {
+                        let _ = outgoing.send(error(Some(id), e.to_string())).await;
+                        continue;
+                    }
+                };
+                let operation_id = params.get("operation_id").cloned();
+                if source == SubscriptionSource::WorkflowStatus {
+                    let Some(operation_id) = operation_id.as_deref() else {
+                        let _ = outgoing.send(error(Some(id), "Workflow subscriptions need an operation_id".to_string())).await;
+                        continue;
+                    };
+                    if let Err(e) = authorize_workflow(&session.temporal_client, &session.tenant_id, operation_id).await {
+                        let _ = outgoing.send(error(Some(id), e.to_string())).await;
+                        continue;
+                    }
+                }
+
+                debug!(subscription = %id, topic = %topic, tenant_id = %session.tenant_id, "WebSocket subscription started");
+                let _ = outgoing.send(ServerMessage::Subscribed { id: id.clone() }).await;
+
+                let task = match source {
+                    SubscriptionSource::WorkflowStatus => tokio::spawn(watch_workflow(
+                        session.clone(),
+                        id.clone(),
+                        operation_id.unwrap_or_default(),
+                        cursor,
+                        outgoing.clone(),
+                    )),
+                    SubscriptionSource::EventStream { service, path } => tokio::spawn(relay_event_stream(
+                        session.clone(),
+                        id.clone(),
+                        service,
+                        path,
+                        cursor,
+                        outgoing.clone(),
+                    )),
+                };
+                subscriptions.insert(id, task);
+            }
+        }
+    }
+
+    for task in subscriptions.into_values() {
+        task.abort();
+    }
+    writer.abort();
+}
+
+/// Workflows of other tenants, and runs not tagged with a tenant, are
+/// refused as if they did not exist
+async fn authorize_workflow(
+    temporal_client: &ApiGatewayTemporalClient,
+    tenant_id: &str,
+    operation_id: &str,
+) -> ApiResult<()> {
+    match temporal_client.get_workflow_tenant(operation_id).await? {
+        Some(owner) if owner == tenant_id => Ok(()),
+        owner => {
+            warn!(tenant_id = %tenant_id, operation_id = %operation_id, owner = ?owner, "Refused subscription to another tenant's workflow");
+            Err(ApiGatewayError::WorkflowNotFound {
+                workflow_id: operation_id.to_string(),
+            })
+        }
+    }
+}
+
+/// Push a workflow's status whenever it changes, until it finishes. The
+/// cursor is the status's update time in milliseconds.
+async fn watch_workflow(
+    session: Arc<Session>,
+    id: String,
+    operation_id: String,
+    cursor: Option<String>,
+    outgoing: mpsc::Sender<ServerMessage>,
+) {
+    let mut last_seen = cursor.and_then(|cursor| cursor.parse::<i64>().ok());
+    let poll_interval = Duration::from_secs(session.config.workflow_poll_seconds.max(1));
+
+    loop {
+        let status = match session.temporal_client.get_workflow_status(&operation_id).await {
+            Ok(status) => status,
+            Err(e) => {
+                let _ = outgoing.send(error(Some(id), e.to_string())).await;
+                return;
+            }
+        };
+
+        let version = status.updated_at.timestamp_millis();
+        let finished = is_finished(&status.status);
+        if !last_seen.is_some_and(|seen| version <= seen) {
+            last_seen = Some(version);
+            let event = ServerMessage::Event {
+                id: id.clone(),
+                cursor: version.to_string(),
+                event: "workflow_status".to_string(),
+                data: serde_json::to_value(&status).unwrap_or(Value::Null),
+            };
+            if outgoing.send(event).await.is_err() {
+                return;
+            }
+        }
+        if finished {
+            let _ = outgoing.send(ServerMessage::Complete { id }).await;
+            return;
+        }
+
+        tokio::time::sleep(poll_interval).await;
+    }
+}
+
+/// Relay a service's event stream for the subscriber's tenant. The cursor is
+/// the stream's event id, sent back as `Last-Event-ID` when the stream is
+/// reconnected.
+async fn relay_event_stream(
+    session: Arc<Session>,
+    id: String,
+    service: String,
+    path: String,
+    mut cursor: Option<String>,
+    outgoing: mpsc::Sender<ServerMessage>,
+) {
+    let route = match session.router.service_route(&service) {
+        Ok(route) => route,
+        Err(e) => {
+            let _ = outgoing.send(error(Some(id), e.to_string())).await;
+            return;
+        }
+    };
+    let url = session.router.build_service_url(&route, &path);
+
+    let mut reconnects = 0;
+    loop {
+        let mut request = session
+            .stream_client
+            .get(&url)
+            .query(&[("tenant_id", session.tenant_id.as_str())])
+            .header(header::ACCEPT.as_str(), "text/event-stream");
+        for (name, value) in &session.headers {
+            request = request.header(name.as_str(), value.as_str());
+        }
+        if let Some(cursor) = &cursor {
+            request = request.header("Last-Event-ID", cursor.as_str());
+        }
+
+        match request.send().await {
+            Ok(response) if response.status().is_success() => {
+                reconnects = 0;
+                let mut parser = EventStreamParser::default();
+                let mut body = response.bytes_stream();
+                while let Some(Ok(chunk)) = body.next().await {
+                    for event in parser.feed(&chunk) {
+                        if event.id.is_some() {
+                            cursor = event.id.clone();
+                        }
+                        let message = ServerMessage::Event {
+                            id: id.clone(),
+                            cursor: cursor.clone().unwrap_or_default(),
+                            event: event.event.unwrap_or_else(|| "message".to_string()),
+                            data: serde_json::from_str(&event.data).unwrap_or(Value::String(event.data)),
+                        };
+                        if outgoing.send(message).await.is_err() {
+                            return;
+                        }
+                    }
+                }
+            }
+            Ok(response) if response.status().is_client_error() => {
+                let message = format!("The {} service refused the subscription with {}", service, response.status());
+                let _ = outgoing.send(error(Some(id), message)).await;
+                return;
+            }
+            Ok(response) => {
+                warn!(service = %service, status = %response.status(), "Event stream failed");
+            }
+            Err(e) => {
+                warn!(service = %service, error = %e, "Event stream connection failed");
+            }
+        }
+
+        reconnects += 1;
+        if reconnects > MAX_STREAM_RECONNECTS {
+            let message = format!("Lost the {} service's event stream", service);
+            let _ = outgoing.send(error(Some(id), message)).await;
+            return;
+        }
+        tokio::time::sleep(Duration::from_secs(1 << reconnects.min(5))).await;
+    }
+}
+
+fn is_finished(status: &WorkflowStatus) -> bool {
+    matches!(
+        status,
+        WorkflowStatus::Completed | WorkflowStatus::Failed | WorkflowStatus::Cancelled | WorkflowStatus::TimedOut
+    )
+}
+
+fn error(id: Option<String>, message: String) -> ServerMessage {
+    ServerMessage::Error { id, message }
+}
+
+/// One server-sent event
+#[derive(Debug, Clone, PartialEq)]
+struct StreamEvent {
+    id: Option<String>,
+    event: Option<String>,
+    data: String,
+}
+
+/// Splits a server-sent event stream into events as its bytes arrive
+#[derive(Debug, Default)]
+struct EventStreamParser {
+    buffer: Vec<u8>,
+    /// The last event id persists across events, as with EventSource
+    last_id: Option<String>,
+    event: Option<String>,
+    data: Vec<String>,
+}
+
+impl EventStreamParser {
+    fn feed(&mut self, chunk: &[u8]) -> Vec<StreamEvent> {
+        self.buffer.extend_from_slice(chunk);
+
+        let mut events = Vec::new();
+        while let Some(end) = self.buffer.iter().position(|byte| *byte == b'\n') {
+            let line: Vec<u8> = self.buffer.drain(..=end).collect();
+            let line = String::from_utf8_lossy(&line);
+            let line = line.trim_end_matches(['\n', '\r']);
+
+            if line.is_empty() {
+                if !self.data.is_empty() {
+                    events.push(StreamEvent {
+                        id: self.last_id.clone(),
+                        event: self.event.take(),
+                        data: self.data.join("\n"),
+                    });
+                    self.data.clear();
+                }
+                self.event = None;
+                continue;
+            }
+            if line.starts_with(':') {
+                continue;
+            }
+
+            let (field, value) = line.split_once(':').unwrap_or((line, ""));
+            let value = value.strip_prefix(' ').unwrap_or(value);
+            match field {
+                "id" => self.last_id = Some(value.to_string()),
+                "event" => self.event = Some(value.to_string()),
+                "data" => self.data.push(value.to_string()),
+                _ => {}
+            }
+        }
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_client_messages() {
+        let subscribe: ClientMessage = serde_json::from_value(json!({
+            "type": "subscribe",
+            "id": "progress",
+            "topic": "workflow",
+            "params": { "operation_id": "op-1" },
+            "cursor": "1700000000000"
+        }))
+        .unwrap();
+        assert!(matches!(
+            subscribe,
+            ClientMessage::Subscribe { ref params, cursor: Some(_), .. } if params["operation_id"] == "op-1"
+        ));
+
+        let unsubscribe: ClientMessage = serde_json::from_value(json!({ "type": "unsubscribe", "id": "progress" })).unwrap();
+        assert_eq!(unsubscribe, ClientMessage::Unsubscribe { id: "progress".to_string() });
+
+        assert!(serde_json::from_value::<ClientMessage>(json!({ "type": "publish" })).is_err());
+    }
+
+    #[test]
+    fn test_server_messages() {
+        let event = serde_json::to_value(ServerMessage::Event {
+            id: "progress".to_string(),
+            cursor: "42".to_string(),
+            event: "workflow_status".to_string(),
+            data: json!({ "status": "running" }),
+        })
+        .unwrap();
+        assert_eq!(event["type"], "event");
+        assert_eq!(event["cursor"], "42");
+
+        let message = serde_json::to_value(error(None, "Invalid message".to_string())).unwrap();
+        assert_eq!(message, json!({ "type": "error", "message": "Invalid message" }));
+    }
+
+    #[test]
+    fn test_query_token_only_on_websocket_path() {
+        let uri: Uri = "/api/v1/ws?access_token=abc.def.ghi".parse().unwrap();
+        assert_eq!(query_token(&uri), Some("abc.def.ghi"));
+
+        let uri: Uri = "/api/v1/users?access_token=abc.def.ghi".parse().unwrap();
+        assert_eq!(query_token(&uri), None);
+
+        let uri: Uri = "/api/v1/ws?access_token=".parse().unwrap();
+        assert_eq!(query_token(&uri), None);
+    }
+
+    #[test]
+    fn test_event_stream_parsing() {
+        let mut parser = EventStreamParser::default();
+
+        let events = parser.feed(b": keep-alive\n\nid: 7\nevent: tenant_suspended\ndata: {\"tenant_id\":");
+        assert!(events.is_empty());
+
+        let events = parser.feed(b"\"t1\"}\r\n\r\ndata: lagged\n\n");
+        assert_eq!(
+            events,
+            vec![
+                StreamEvent {
+                    id: Some("7".to_string()),
+                    event: Some("tenant_suspended".to_string()),
+                    data: "{\"tenant_id\":\"t1\"}".to_string(),
+                },
+                StreamEvent {
+                    id: Some("7".to_string()),
+                    event: None,
+                    data: "lagged".to_string(),
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_finished_workflows() {
+        assert!(is_finished(&WorkflowStatus::Completed));
+        assert!(is_finished(&WorkflowStatus::TimedOut));
+        assert!(!is_finished(&WorkflowStatus::Running));
+    }
+
+    /// A Temporal HTTP API describing `wf-tenant-1` as tenant-1's and
+    /// `wf-untagged` without a tenant
+    async fn temporal_client() -> ApiGatewayTemporalClient {
+        use axum::extract::Path;
+        use axum::http::StatusCode;
+        use axum::routing::get;
+
+        let describe = |Path((_, workflow_id)): Path<(String, String)>| async move {
+            let fields = match workflow_id.as_str() {
+                "wf-tenant-1" => json!({ "TenantId": "tenant-1" }),
+                "wf-untagged" => json!({}),
+                _ => return Err(StatusCode::NOT_FOUND),
+            };
+            Ok(axum::Json(json!({
+                "workflowExecutionInfo": { "searchAttributes": { "indexedFields": fields } }
+            })))
+        };
+        let app = axum::Router::new().route("/api/v1/namespaces/:namespace/workflows/:workflow_id", get(describe));
+        let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+        let address = listener.local_addr().unwrap();
+        tokio::spawn(async move { axum::serve(listener, app).await });
+
+        ApiGatewayTemporalClient::new(crate::config::TemporalConfig {
+            server_address: address.to_string(),
+            namespace: "test".to_string(),
+            client_identity: "api-gateway-test".to_string(),
+            connection_timeout_seconds: 5,
+            request_timeout_seconds: 5,
+        })
+        .await
+        .unwrap()
+    }
+
+    #[tokio::test]
+    async fn test_workflow_subscriptions_are_scoped_to_the_tenant() {
+        let client = temporal_client().await;
+
+        assert!(authorize_workflow(&client, "tenant-1", "wf-tenant-1").await.is_ok());
+        assert!(matches!(
+            authorize_workflow(&client, "tenant-2", "wf-tenant-1").await,
+            Err(ApiGatewayError::WorkflowNotFound { .. })
+        ));
+        assert!(matches!(
+            authorize_workflow(&client, "tenant-1", "wf-untagged").await,
+            Err(ApiGatewayError::WorkflowNotFound { .. })
+        ));
+        assert!(matches!(
+            authorize_workflow(&client, "tenant-1", "wf-missing").await,
+            Err(ApiGatewayError::WorkflowNotFound { .. })
+        ));
+    }
+}
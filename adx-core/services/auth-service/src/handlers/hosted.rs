@@ -0,0 +1,98 @@
+use axum::{
+    extract::Query,
+    http::{header, HeaderMap, HeaderValue},
+    response::{Html, IntoResponse, Redirect, Response},
+};
+use serde::Deserialize;
+use std::sync::OnceLock;
+use uuid::Uuid;
+
+use adx_shared::login_page::{
+    is_safe_redirect, render_page, AuthPage, HostedLoginPage, LoginPageClient, RenderContext,
+};
+
+static LOGIN_PAGES: OnceLock<LoginPageClient> = OnceLock::new();
+
+#[derive(Debug, Deserialize)]
+pub struct HostedPageQuery {
+    /// Path to go to after signing in
+    pub redirect: Option<String>,
+    pub locale: Option<String>,
+}
+
+/// Hosted sign-in page, in the branding and layout the domain's tenant
+/// published in white-label-service
+pub async fn hosted_login(headers: HeaderMap, Query(query): Query<HostedPageQuery>) -> Response {
+    hosted_page(AuthPage::Login, &headers, query).await
+}
+
+/// Hosted registration page; sends visitors to sign-in when the tenant
+/// does not allow registration
+pub async fn hosted_register(headers: HeaderMap, Query(query): Query<HostedPageQuery>) -> Response {
+    hosted_page(AuthPage::Register, &headers, query).await
+}
+
+async fn hosted_page(kind: AuthPage, headers: &HeaderMap, query: HostedPageQuery) -> Response {
+    let page = login_page_for(headers).await;
+    if kind == AuthPage::Register && !page.config.allow_registration {
+        return Redirect::to("login").into_response();
+    }
+
+    let accept_language = headers
+        .get(header::ACCEPT_LANGUAGE)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    let preferred = query
+        .locale
+        .as_deref()
+        .into_iter()
+        .chain(accept_language.split(',').map(|part| part.split(';').next().unwrap_or_default()));
+    let locale = page.config.resolve_locale(preferred).to_string();
+
+    let nonce = Uuid::new_v4().simple().to_string();
+    let html = render_page(
+        &page,
+        kind,
+        &RenderContext {
+            locale,
+            api_base: "/api/v1/auth".to_string(),
+            redirect_path: query.redirect.filter(|path| is_safe_redirect(path)),
+            nonce: nonce.clone(),
+            preview: false,
+        },
+    );
+
+    let csp = format!(
+        "default-src 'self'; img-src 'self' https: data:; style-src 'nonce-{nonce}'; \
+         script-src 'nonce-{nonce}'; connect-src 'self'; form-action 'self'; frame-ancestors 'none'"
+    );
+    let mut response = Html(html).into_response();
+    let response_headers = response.headers_mut();
+    response_headers.insert(header::CACHE_CONTROL, HeaderValue::from_static("no-store"));
+    if let Ok(csp) = HeaderValue::from_str(&csp) {
+        response_headers.insert(header::CONTENT_SECURITY_POLICY, csp);
+    }
+    response
+}
+
+/// The published login page of the requested domain. Platform domains, and
+/// any failure to reach white-label-service, get the default page.
+async fn login_page_for(headers: &HeaderMap) -> HostedLoginPage {
+    let host = headers
+        .get("X-Forwarded-Host")
+        .or_else(|| headers.get(header::HOST))
+        .and_then(|value| value.to_str().ok())
+        .map(|host| host.split(':').next().unwrap_or(host).to_string());
+    let Some(host) = host else {
+        return HostedLoginPage::default();
+    };
+
+    let client = LOGIN_PAGES.get_or_init(LoginPageClient::from_env);
+    match client.published_for_domain(&host).await {
+        Ok(page) => page.unwrap_or_default(),
+        Err(e) => {
+            tracing::warn!(host = %host, error = %e, "Failed to load login page, using the default");
+            HostedLoginPage::default()
+        }
+    }
+}
@@ -1,7 +1,9 @@
 pub mod auth;
 pub mod users;
 pub mod health;
+pub mod hosted;
 
 pub use auth::*;
 pub use users::*;
-pub use health::*;
\ No newline at end of file
+pub use health::*;
+pub use hosted::*;
\ No newline at end of file
@@ -121,7 +121,10 @@ pub async fn security_headers_middleware(
     headers.insert("X-Frame-Options", "DENY".parse().unwrap());
     headers.insert("X-XSS-Protection", "1; mode=block".parse().unwrap());
     headers.insert("Referrer-Policy", "strict-origin-when-cross-origin".parse().unwrap());
-    headers.insert("Content-Security-Policy", "default-src 'self'".parse().unwrap());
+    // Pages that need inline styles or scripts set their own policy
+    if !headers.contains_key("Content-Security-Policy") {
+        headers.insert("Content-Security-Policy", "default-src 'self'".parse().unwrap());
+    }
     
     response
 }
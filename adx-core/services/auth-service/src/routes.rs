@@ -5,7 +5,7 @@ use axum::{
 };
 
 use crate::{
-    handlers::{auth, users, health},
+    handlers::{auth, users, health, hosted},
     middleware::{
         auth::auth_middleware,
         tenant::tenant_context_middleware,
@@ -21,7 +21,10 @@ pub fn create_routes(state: AppState) -> Router {
         .route("/health", get(health::health_check))
         .route("/auth/register", post(auth::register))
         .route("/auth/login", post(auth::login))
-        .route("/auth/password-reset", post(auth::request_password_reset));
+        .route("/auth/password-reset", post(auth::request_password_reset))
+        // Tenant-branded sign-in and registration pages
+        .route("/auth/hosted/login", get(hosted::hosted_login))
+        .route("/auth/hosted/register", get(hosted::hosted_register));
 
     // Protected routes (authentication required)
     let protected_routes = Router::new()
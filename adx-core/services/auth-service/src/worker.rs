@@ -317,6 +317,8 @@ impl AuthWorker {
                     "mfa-setup-queue".to_string(),
                     "sso-authentication-queue".to_string(),
                 ],
+                task_queue_limits: std::collections::HashMap::new(),
+                task_queue_lanes: std::collections::HashMap::new(),
                 enable_sticky_execution: true,
                 sticky_schedule_to_start_timeout: std::time::Duration::from_secs(10),
             },
@@ -10,6 +10,7 @@ use crate::{
     models::*,
     repositories::*,
     storage::StorageManager,
+    validation::validate_file_type,
 };
 
 // Activity request/response types
@@ -119,6 +120,7 @@ pub trait FileActivities: Send + Sync {
 pub struct FileActivitiesImpl {
     file_repo: Arc<dyn FileRepository>,
     permission_repo: Arc<dyn FilePermissionRepository>,
+    policy_repo: Arc<dyn FileTypePolicyRepository>,
     storage_manager: Arc<StorageManager>,
 }
 
@@ -126,11 +128,13 @@ impl FileActivitiesImpl {
     pub fn new(
         file_repo: Arc<dyn FileRepository>,
         permission_repo: Arc<dyn FilePermissionRepository>,
+        policy_repo: Arc<dyn FileTypePolicyRepository>,
         storage_manager: Arc<StorageManager>,
     ) -> Self {
         Self {
             file_repo,
             permission_repo,
+            policy_repo,
             storage_manager,
         }
     }
@@ -151,6 +155,22 @@ impl FileActivities for FileActivitiesImpl {
                 resource_id: request.file_id.to_string() 
             })?;
 
+        // Enforce the tenant file type policy against the sniffed content
+        let policy = match self.policy_repo
+            .get_by_tenant(&request.tenant_context)
+            .await
+            .map_err(|e| ActivityError::DatabaseError { message: format!("Failed to get file type policy: {}", e) })?
+        {
+            Some(policy) => policy,
+            None => FileTypePolicy::default_for_tenant(file.tenant_id),
+        };
+
+        let validation = validate_file_type(&policy, &file.original_filename, &file.mime_type, &request.file_data)
+            .map_err(|violation| ActivityError::ValidationError {
+                field: "file_type".to_string(),
+                message: violation.to_string(),
+            })?;
+
         // Upload to storage
         let storage_url = self.storage_manager
             .upload(None, &file.storage_path, &request.file_data)
@@ -169,6 +189,13 @@ impl FileActivities for FileActivitiesImpl {
             .await
             .map_err(|e| ActivityError::DatabaseError { message: format!("Failed to update file info: {}", e) })?;
 
+        if validation.effective_mime_type != file.mime_type {
+            self.file_repo
+                .update_mime_type(request.file_id, &validation.effective_mime_type, &request.tenant_context)
+                .await
+                .map_err(|e| ActivityError::DatabaseError { message: format!("Failed to update file type: {}", e) })?;
+        }
+
         self.file_repo
             .update_status(request.file_id, FileStatus::Processing, &request.tenant_context)
             .await
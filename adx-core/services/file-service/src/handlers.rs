@@ -7,7 +7,7 @@ use axum::{
 };
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
-use adx_shared::{TenantContext, UserContext, Result, Error};
+use adx_shared::{TenantContext, UserContext, Result, Error, ServiceError};
 use crate::models::*;
 use crate::services::FileService;
 
@@ -39,6 +39,13 @@ impl FileHandlers {
     ) -> Result<Json<FileUploadResponse>, (StatusCode, Json<serde_json::Value>)> {
         match handlers.file_service.create_file(&request, &tenant_context, &user_context).await {
             Ok(response) => Ok(Json(response)),
+            Err(ServiceError::QuotaExceeded(message)) => Err((
+                StatusCode::PAYLOAD_TOO_LARGE,
+                Json(serde_json::json!({
+                    "error": "File too large",
+                    "details": message
+                }))
+            )),
             Err(e) => {
                 tracing::error!("Failed to create file: {}", e);
                 Err((
@@ -207,6 +214,21 @@ impl FileHandlers {
 
         match handlers.file_service.upload_file_data(file_id, &file_data, &tenant_context, &user_context).await {
             Ok(()) => Ok(StatusCode::OK),
+            Err(ServiceError::QuotaExceeded(message)) => Err((
+                StatusCode::PAYLOAD_TOO_LARGE,
+                Json(serde_json::json!({
+                    "error": "File too large",
+                    "details": message
+                }))
+            )),
+            Err(ServiceError::ContentRejected { code, message }) => Err((
+                StatusCode::UNSUPPORTED_MEDIA_TYPE,
+                Json(serde_json::json!({
+                    "error": "File type not permitted",
+                    "code": code,
+                    "details": message
+                }))
+            )),
             Err(e) => {
                 tracing::error!("Failed to upload file data: {}", e);
                 let status = if e.to_string().contains("Permission denied") {
@@ -405,6 +427,52 @@ impl FileHandlers {
         }
     }
 
+    pub async fn get_file_type_policy(
+        State(handlers): State<Arc<FileHandlers>>,
+        Extension(tenant_context): Extension<TenantContext>,
+    ) -> Result<Json<FileTypePolicy>, (StatusCode, Json<serde_json::Value>)> {
+        match handlers.file_service.get_file_type_policy(&tenant_context).await {
+            Ok(policy) => Ok(Json(policy)),
+            Err(e) => {
+                tracing::error!("Failed to get file type policy: {}", e);
+                Err((
+                    StatusCode::INTERNAL_SERVER_ERROR,
+                    Json(serde_json::json!({
+                        "error": "Failed to get file type policy",
+                        "details": e.to_string()
+                    }))
+                ))
+            }
+        }
+    }
+
+    pub async fn update_file_type_policy(
+        State(handlers): State<Arc<FileHandlers>>,
+        Extension(tenant_context): Extension<TenantContext>,
+        Extension(user_context): Extension<UserContext>,
+        Json(request): Json<UpdateFileTypePolicyRequest>,
+    ) -> Result<Json<FileTypePolicy>, (StatusCode, Json<serde_json::Value>)> {
+        match handlers.file_service.update_file_type_policy(&request, &tenant_context, &user_context).await {
+            Ok(policy) => Ok(Json(policy)),
+            Err(e) => {
+                tracing::error!("Failed to update file type policy: {}", e);
+                let status = if e.to_string().contains("Permission denied") {
+                    StatusCode::FORBIDDEN
+                } else {
+                    StatusCode::INTERNAL_SERVER_ERROR
+                };
+                
+                Err((
+                    status,
+                    Json(serde_json::json!({
+                        "error": "Failed to update file type policy",
+                        "details": e.to_string()
+                    }))
+                ))
+            }
+        }
+    }
+
     pub async fn health_check() -> Result<Json<serde_json::Value>, (StatusCode, Json<serde_json::Value>)> {
         Ok(Json(serde_json::json!({
             "status": "healthy",
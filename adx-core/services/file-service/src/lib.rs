@@ -7,6 +7,7 @@ pub mod activities;
 pub mod workflows;
 pub mod storage;
 pub mod services;
+pub mod validation;
 
 // Re-export commonly used types
 pub use models::*;
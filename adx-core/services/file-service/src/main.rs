@@ -10,6 +10,7 @@ mod activities;
 mod workflows;
 mod storage;
 mod services;
+mod validation;
 
 use server::start_server;
 use worker::start_worker;
@@ -114,6 +114,45 @@ pub enum StorageProviderType {
     Ftp,
 }
 
+/// Per-tenant file type policy enforced on every upload
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct FileTypePolicy {
+    pub tenant_id: Uuid,
+    /// MIME types (or `type/*` wildcards) accepted; empty means all non-blocked types
+    pub allowed_mime_types: Vec<String>,
+    pub blocked_mime_types: Vec<String>,
+    pub blocked_extensions: Vec<String>,
+    /// Reject uploads whose content does not match the declared type
+    pub enforce_content_match: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl FileTypePolicy {
+    /// Policy applied to tenants that have not configured their own
+    pub fn default_for_tenant(tenant_id: Uuid) -> Self {
+        let now = Utc::now();
+        Self {
+            tenant_id,
+            allowed_mime_types: Vec::new(),
+            blocked_mime_types: vec![
+                "application/x-msdownload".to_string(),
+                "application/x-executable".to_string(),
+                "application/x-mach-binary".to_string(),
+                "application/x-sh".to_string(),
+                "text/x-shellscript".to_string(),
+            ],
+            blocked_extensions: ["exe", "dll", "bat", "cmd", "com", "scr", "msi", "ps1", "vbs", "sh"]
+                .iter()
+                .map(|ext| ext.to_string())
+                .collect(),
+            enforce_content_match: true,
+            created_at: now,
+            updated_at: now,
+        }
+    }
+}
+
 // Request/Response DTOs
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateFileRequest {
@@ -147,6 +186,14 @@ pub struct CreateFilePermissionRequest {
     pub expires_at: Option<DateTime<Utc>>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateFileTypePolicyRequest {
+    pub allowed_mime_types: Option<Vec<String>>,
+    pub blocked_mime_types: Option<Vec<String>>,
+    pub blocked_extensions: Option<Vec<String>>,
+    pub enforce_content_match: Option<bool>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct FileUploadResponse {
     pub file_id: Uuid,
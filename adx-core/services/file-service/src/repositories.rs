@@ -14,6 +14,7 @@ pub trait FileRepository: Send + Sync {
     async fn list(&self, tenant_context: &TenantContext, user_id: Option<Uuid>, page: i32, per_page: i32) -> Result<FileListResponse>;
     async fn update_status(&self, id: Uuid, status: FileStatus, tenant_context: &TenantContext) -> Result<()>;
     async fn update_storage_info(&self, id: Uuid, storage_path: &str, checksum: Option<&str>, tenant_context: &TenantContext) -> Result<()>;
+    async fn update_mime_type(&self, id: Uuid, mime_type: &str, tenant_context: &TenantContext) -> Result<()>;
 }
 
 #[async_trait]
@@ -33,6 +34,12 @@ pub trait FileShareRepository: Send + Sync {
     async fn deactivate(&self, id: Uuid, tenant_context: &TenantContext) -> Result<()>;
 }
 
+#[async_trait]
+pub trait FileTypePolicyRepository: Send + Sync {
+    async fn get_by_tenant(&self, tenant_context: &TenantContext) -> Result<Option<FileTypePolicy>>;
+    async fn upsert(&self, updates: &UpdateFileTypePolicyRequest, tenant_context: &TenantContext) -> Result<FileTypePolicy>;
+}
+
 #[async_trait]
 pub trait StorageProviderRepository: Send + Sync {
     async fn create(&self, provider: &StorageProvider, tenant_context: &TenantContext) -> Result<StorageProvider>;
@@ -279,6 +286,24 @@ impl FileRepository for PostgresFileRepository {
 
         Ok(())
     }
+
+    async fn update_mime_type(&self, id: Uuid, mime_type: &str, tenant_context: &TenantContext) -> Result<()> {
+        let result = sqlx::query!(
+            "UPDATE files SET mime_type = $3, updated_at = NOW() WHERE id = $1 AND tenant_id = $2",
+            id,
+            tenant_context.tenant_id,
+            mime_type
+        )
+        .execute(&self.pool)
+        .await
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+        if result.rows_affected() == 0 {
+            return Err(Error::NotFound("File not found".to_string()));
+        }
+
+        Ok(())
+    }
 }
 
 pub struct PostgresFilePermissionRepository {
@@ -516,4 +541,74 @@ impl FileShareRepository for PostgresFileShareRepository {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+pub struct PostgresFileTypePolicyRepository {
+    pool: PgPool,
+}
+
+impl PostgresFileTypePolicyRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl FileTypePolicyRepository for PostgresFileTypePolicyRepository {
+    async fn get_by_tenant(&self, tenant_context: &TenantContext) -> Result<Option<FileTypePolicy>> {
+        let result = sqlx::query_as!(
+            FileTypePolicy,
+            r#"
+            SELECT 
+                tenant_id, allowed_mime_types, blocked_mime_types, blocked_extensions,
+                enforce_content_match, created_at, updated_at
+            FROM file_type_policies 
+            WHERE tenant_id = $1
+            "#,
+            tenant_context.tenant_id
+        )
+        .fetch_optional(&self.pool)
+        .await
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+        Ok(result)
+    }
+
+    async fn upsert(&self, updates: &UpdateFileTypePolicyRequest, tenant_context: &TenantContext) -> Result<FileTypePolicy> {
+        let tenant_id = Uuid::parse_str(&tenant_context.tenant_id)
+            .map_err(|e| Error::Validation(format!("Invalid tenant ID format: {}", e)))?;
+        let defaults = FileTypePolicy::default_for_tenant(tenant_id);
+
+        let result = sqlx::query_as!(
+            FileTypePolicy,
+            r#"
+            INSERT INTO file_type_policies (
+                tenant_id, allowed_mime_types, blocked_mime_types, blocked_extensions, enforce_content_match
+            )
+            VALUES ($1, COALESCE($2, $6), COALESCE($3, $7), COALESCE($4, $8), COALESCE($5, $9))
+            ON CONFLICT (tenant_id) DO UPDATE SET
+                allowed_mime_types = COALESCE($2, file_type_policies.allowed_mime_types),
+                blocked_mime_types = COALESCE($3, file_type_policies.blocked_mime_types),
+                blocked_extensions = COALESCE($4, file_type_policies.blocked_extensions),
+                enforce_content_match = COALESCE($5, file_type_policies.enforce_content_match),
+                updated_at = NOW()
+            RETURNING 
+                tenant_id, allowed_mime_types, blocked_mime_types, blocked_extensions,
+                enforce_content_match, created_at, updated_at
+            "#,
+            tenant_id,
+            updates.allowed_mime_types.as_deref(),
+            updates.blocked_mime_types.as_deref(),
+            updates.blocked_extensions.as_deref(),
+            updates.enforce_content_match,
+            &defaults.allowed_mime_types,
+            &defaults.blocked_mime_types,
+            &defaults.blocked_extensions,
+            defaults.enforce_content_match
+        )
+        .fetch_one(&self.pool)
+        .await
+        .map_err(|e| Error::Database(e.to_string()))?;
+
+        Ok(result)
+    }
+}
@@ -12,9 +12,12 @@ use tower_http::{
 };
 use sqlx::PgPool;
 use adx_shared::{
+    audit::AuditEmitter,
     config::AppConfig,
     database::DatabasePool,
+    dlp::DlpClient,
     middleware::{tenant_context_middleware, auth_middleware},
+    quotas::QuotaClient,
 };
 use crate::{
     handlers::FileHandlers,
@@ -41,6 +44,7 @@ impl FileServer {
         let file_repo = Arc::new(PostgresFileRepository::new(self.pool.clone()));
         let permission_repo = Arc::new(PostgresFilePermissionRepository::new(self.pool.clone()));
         let share_repo = Arc::new(PostgresFileShareRepository::new(self.pool.clone()));
+        let policy_repo = Arc::new(PostgresFileTypePolicyRepository::new(self.pool.clone()));
 
         // Initialize storage manager
         let mut storage_manager = StorageManager::new();
@@ -58,12 +62,24 @@ impl FileServer {
 
         let storage_manager = Arc::new(storage_manager);
 
+        // gRPC when LICENSE_SERVICE_GRPC_URL is set
+        let quota_client = Arc::new(QuotaClient::from_env()?);
+
+        let security_service_url = std::env::var("SECURITY_SERVICE_URL")
+            .unwrap_or_else(|_| "http://localhost:8087".to_string());
+        let dlp_client = Arc::new(DlpClient::from_url(&security_service_url));
+        let audit = AuditEmitter::from_url(&security_service_url);
+
         // Initialize services
         let file_service = Arc::new(FileService::new(
             file_repo,
             permission_repo,
             share_repo,
+            policy_repo,
             storage_manager,
+            quota_client,
+            dlp_client,
+            audit,
         ));
 
         // Initialize handlers
@@ -105,6 +121,10 @@ impl FileServer {
             .route("/api/v1/files/:file_id/permissions", post(FileHandlers::grant_file_permission))
             .route("/api/v1/files/:file_id/permissions", get(FileHandlers::get_file_permissions))
             
+            // Tenant file type policy endpoints
+            .route("/api/v1/file-type-policy", get(FileHandlers::get_file_type_policy))
+            .route("/api/v1/file-type-policy", put(FileHandlers::update_file_type_policy))
+            
             // Public share access endpoint (no auth required)
             .route("/api/v1/shares/:share_token", post(FileHandlers::access_shared_file))
             
@@ -1,15 +1,25 @@
 use std::sync::Arc;
 use uuid::Uuid;
-use adx_shared::{Result, TenantContext, UserContext};
+use adx_shared::{
+    audit::AuditEmitter,
+    dlp::{DlpChannel, DlpClient},
+    quotas::{QuotaClient, FILE_UPLOAD_SIZE_QUOTA},
+    Result, ServiceError, TenantContext, UserContext,
+};
 use crate::models::*;
 use crate::repositories::*;
 use crate::storage::StorageManager;
+use crate::validation::validate_file_type;
 
 pub struct FileService {
     file_repo: Arc<dyn FileRepository>,
     permission_repo: Arc<dyn FilePermissionRepository>,
     share_repo: Arc<dyn FileShareRepository>,
+    policy_repo: Arc<dyn FileTypePolicyRepository>,
     storage_manager: Arc<StorageManager>,
+    quota_client: Arc<QuotaClient>,
+    dlp_client: Arc<DlpClient>,
+    audit: AuditEmitter,
 }
 
 impl FileService {
@@ -17,13 +27,21 @@ impl FileService {
         file_repo: Arc<dyn FileRepository>,
         permission_repo: Arc<dyn FilePermissionRepository>,
         share_repo: Arc<dyn FileShareRepository>,
+        policy_repo: Arc<dyn FileTypePolicyRepository>,
         storage_manager: Arc<StorageManager>,
+        quota_client: Arc<QuotaClient>,
+        dlp_client: Arc<DlpClient>,
+        audit: AuditEmitter,
     ) -> Self {
         Self {
             file_repo,
             permission_repo,
             share_repo,
+            policy_repo,
             storage_manager,
+            quota_client,
+            dlp_client,
+            audit,
         }
     }
 
@@ -36,6 +54,8 @@ impl FileService {
         let user_uuid = Uuid::parse_str(&user_context.user_id)
             .map_err(|e| anyhow::anyhow!("Invalid user ID format: {}", e))?;
         
+        self.require_upload_size(tenant_context, request.file_size).await?;
+        
         // Create file record
         let file = self.file_repo.create(request, tenant_context, user_uuid).await?;
         
@@ -170,6 +190,25 @@ impl FileService {
             return Err(anyhow::anyhow!("Permission denied"));
         }
 
+        // The declared size was checked when the file was created; the data
+        // sent may be larger
+        self.require_upload_size(tenant_context, data.len() as i64).await?;
+
+        // Validate the actual content against the tenant's file type policy
+        // before anything reaches storage
+        let policy = self.get_file_type_policy(tenant_context).await?;
+        let validation = validate_file_type(&policy, &file.original_filename, &file.mime_type, data)
+            .map_err(|violation| {
+                tracing::warn!("Rejected upload for file {}: {}", file_id, violation);
+                ServiceError::ContentRejected {
+                    code: violation.code(),
+                    message: violation.to_string(),
+                }
+            })?;
+
+        let redacted = self.apply_dlp_policy(&file, data, tenant_context, user_context).await?;
+        let data = redacted.as_deref().unwrap_or(data);
+
         // Upload to storage
         let storage_url = self.storage_manager.upload(None, &file.storage_path, data).await?;
         
@@ -178,11 +217,78 @@ impl FileService {
 
         // Update file status and storage info
         self.file_repo.update_storage_info(file_id, &storage_url, Some(&checksum), tenant_context).await?;
+        if validation.effective_mime_type != file.mime_type {
+            self.file_repo.update_mime_type(file_id, &validation.effective_mime_type, tenant_context).await?;
+        }
         self.file_repo.update_status(file_id, FileStatus::Ready, tenant_context).await?;
 
         Ok(())
     }
 
+    /// Scan text content against the tenant's DLP rules and record what
+    /// matched. Returns the redacted content when a rule redacts. Binary
+    /// content is not scanned, and uploads go ahead when security-service
+    /// cannot be reached.
+    async fn apply_dlp_policy(
+        &self,
+        file: &File,
+        data: &[u8],
+        tenant_context: &TenantContext,
+        user_context: &UserContext,
+    ) -> Result<Option<Vec<u8>>> {
+        let Ok(text) = std::str::from_utf8(data) else {
+            return Ok(None);
+        };
+
+        let result = match self.dlp_client.scan(&tenant_context.tenant_id, DlpChannel::FileUpload, text).await {
+            Ok(result) => result,
+            Err(e) => {
+                tracing::warn!("Could not load DLP policy of tenant {}: {}", tenant_context.tenant_id, e);
+                return Ok(None);
+            }
+        };
+
+        let file_id = file.id.to_string();
+        if let Some(incident) = result.incident(
+            &tenant_context.tenant_id,
+            "file-service",
+            "file",
+            Some(&file_id),
+            Some(&user_context.user_id),
+        ) {
+            self.audit.emit(incident);
+        }
+
+        if result.is_blocked() {
+            tracing::warn!("Rejected upload for file {}: DLP rules {:?} matched", file.id, result.blocking_rules());
+            return Err(ServiceError::Validation(format!(
+                "Upload rejected: content matches data loss prevention rules ({})",
+                result.blocking_rules().join(", ")
+            )));
+        }
+
+        Ok(result.is_redacted().then(|| result.text.into_bytes()))
+    }
+
+    /// Refuse files larger than the tenant's plan allows. Uploads go ahead
+    /// when license-service cannot be reached.
+    async fn require_upload_size(&self, tenant_context: &TenantContext, size_bytes: i64) -> Result<()> {
+        const MB: i64 = 1024 * 1024;
+        let size_mb = (size_bytes + MB - 1) / MB;
+
+        match self.quota_client.require(&tenant_context.tenant_id, FILE_UPLOAD_SIZE_QUOTA, size_mb).await {
+            Ok(_) => Ok(()),
+            Err(ServiceError::QuotaExceeded(_)) => Err(ServiceError::QuotaExceeded(format!(
+                "Files of {} MB are above the upload size allowed by the plan",
+                size_mb
+            ))),
+            Err(e) => {
+                tracing::warn!("Could not check upload size quota of tenant {}: {}", tenant_context.tenant_id, e);
+                Ok(())
+            }
+        }
+    }
+
     pub async fn download_file(
         &self,
         file_id: Uuid,
@@ -367,4 +473,28 @@ impl FileService {
 
         self.permission_repo.get_by_file_id(file_id, tenant_context).await
     }
-}
\ No newline at end of file
+
+    pub async fn get_file_type_policy(&self, tenant_context: &TenantContext) -> Result<FileTypePolicy> {
+        match self.policy_repo.get_by_tenant(tenant_context).await? {
+            Some(policy) => Ok(policy),
+            None => {
+                let tenant_uuid = Uuid::parse_str(&tenant_context.tenant_id)
+                    .map_err(|e| anyhow::anyhow!("Invalid tenant ID format: {}", e))?;
+                Ok(FileTypePolicy::default_for_tenant(tenant_uuid))
+            }
+        }
+    }
+
+    pub async fn update_file_type_policy(
+        &self,
+        request: &UpdateFileTypePolicyRequest,
+        tenant_context: &TenantContext,
+        user_context: &UserContext,
+    ) -> Result<FileTypePolicy> {
+        if !user_context.roles.contains(&"admin".to_string()) {
+            return Err(anyhow::anyhow!("Permission denied"));
+        }
+
+        self.policy_repo.upsert(request, tenant_context).await
+    }
+}
@@ -0,0 +1,367 @@
+// Content-based file type validation
+//
+// Client-declared content types cannot be trusted, so uploads are sniffed from
+// their leading bytes and checked against the tenant's file type policy before
+// anything is written to storage.
+
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+use crate::models::FileTypePolicy;
+
+/// Number of leading bytes inspected when sniffing content
+const SNIFF_WINDOW: usize = 8192;
+
+/// Signatures shorter than this also start ordinary text ("BM" in a CSV
+/// header, "ID3" in a note), so they only count for binary content
+const SHORT_SIGNATURE_LEN: usize = 4;
+
+/// Interpreter paths a shebang must name to mark a script
+const SHEBANG_PREFIXES: [&str; 3] = ["#!/bin/", "#!/usr/bin/", "#!/usr/local/bin/"];
+
+pub const MIME_OCTET_STREAM: &str = "application/octet-stream";
+
+/// Errors raised when an upload violates the tenant's file type policy
+#[derive(Debug, Clone, Error, Serialize, Deserialize, PartialEq)]
+pub enum FileTypeViolation {
+    #[error("FILE_EXTENSION_BLOCKED: files with extension '.{extension}' are not permitted")]
+    ExtensionBlocked { extension: String },
+
+    #[error("FILE_TYPE_BLOCKED: content type '{mime_type}' is blocked by tenant policy")]
+    TypeBlocked { mime_type: String },
+
+    #[error("FILE_TYPE_NOT_ALLOWED: content type '{mime_type}' is not in the tenant's allowed types")]
+    TypeNotAllowed { mime_type: String },
+
+    #[error("FILE_TYPE_MISMATCH: declared type '{declared}' does not match detected content '{detected}'")]
+    ContentMismatch { declared: String, detected: String },
+}
+
+impl FileTypeViolation {
+    pub fn code(&self) -> &'static str {
+        match self {
+            FileTypeViolation::ExtensionBlocked { .. } => "FILE_EXTENSION_BLOCKED",
+            FileTypeViolation::TypeBlocked { .. } => "FILE_TYPE_BLOCKED",
+            FileTypeViolation::TypeNotAllowed { .. } => "FILE_TYPE_NOT_ALLOWED",
+            FileTypeViolation::ContentMismatch { .. } => "FILE_TYPE_MISMATCH",
+        }
+    }
+}
+
+/// Outcome of a successful validation
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FileTypeValidation {
+    pub declared_mime_type: String,
+    pub detected_mime_type: Option<String>,
+    /// The type that should be persisted for the file
+    pub effective_mime_type: String,
+}
+
+/// Detect a MIME type from file content using magic byte signatures.
+///
+/// Returns `None` when the content is binary and matches no known signature.
+pub fn sniff_mime_type(data: &[u8]) -> Option<&'static str> {
+    let head = &data[..data.len().min(SNIFF_WINDOW)];
+
+    let signatures: &[(&[u8], &str)] = &[
+        (b"\x89PNG\r\n\x1a\n", "image/png"),
+        (b"\xFF\xD8\xFF", "image/jpeg"),
+        (b"GIF87a", "image/gif"),
+        (b"GIF89a", "image/gif"),
+        (b"%PDF-", "application/pdf"),
+        (b"PK\x03\x04", "application/zip"),
+        (b"PK\x05\x06", "application/zip"),
+        (b"\x1F\x8B", "application/gzip"),
+        (b"7z\xBC\xAF\x27\x1C", "application/x-7z-compressed"),
+        (b"Rar!\x1A\x07", "application/vnd.rar"),
+        (b"\xD0\xCF\x11\xE0\xA1\xB1\x1A\xE1", "application/x-ole-storage"),
+        (b"MZ", "application/x-msdownload"),
+        (b"\x7FELF", "application/x-executable"),
+        (b"\xCA\xFE\xBA\xBE", "application/x-mach-binary"),
+        (b"\xFE\xED\xFA\xCE", "application/x-mach-binary"),
+        (b"\xFE\xED\xFA\xCF", "application/x-mach-binary"),
+        (b"\xCF\xFA\xED\xFE", "application/x-mach-binary"),
+        (b"ID3", "audio/mpeg"),
+        (b"OggS", "audio/ogg"),
+        (b"fLaC", "audio/flac"),
+        (b"\x1A\x45\xDF\xA3", "video/webm"),
+        (b"BM", "image/bmp"),
+    ];
+
+    let text = decode_text(head);
+    let matched = signatures.iter().find(|(magic, _)| {
+        head.starts_with(magic) && (magic.len() >= SHORT_SIGNATURE_LEN || text.is_none())
+    });
+    if let Some((_, mime)) = matched {
+        return Some(mime);
+    }
+
+    if head.len() >= 12 && head.starts_with(b"RIFF") {
+        match &head[8..12] {
+            b"WEBP" => return Some("image/webp"),
+            b"WAVE" => return Some("audio/wav"),
+            b"AVI " => return Some("video/x-msvideo"),
+            _ => {}
+        }
+    }
+
+    if head.len() >= 12 && &head[4..8] == b"ftyp" {
+        return match &head[8..12] {
+            b"heic" | b"heix" | b"mif1" => Some("image/heic"),
+            b"qt  " => Some("video/quicktime"),
+            _ => Some("video/mp4"),
+        };
+    }
+
+    if text.is_none() && head.len() >= 2 && head[0] == 0xFF && (head[1] & 0xE0) == 0xE0 {
+        return Some("audio/mpeg");
+    }
+
+    text.map(sniff_text)
+}
+
+/// The content as UTF-8 text, or None when it is binary
+fn decode_text(head: &[u8]) -> Option<&str> {
+    if head.is_empty() || head.contains(&0) {
+        return None;
+    }
+
+    // A multi-byte character may be cut off at the end of the sniff window
+    match std::str::from_utf8(head) {
+        Ok(text) => Some(text),
+        Err(e) if e.error_len().is_none() => std::str::from_utf8(&head[..e.valid_up_to()]).ok(),
+        Err(_) => None,
+    }
+}
+
+fn sniff_text(text: &str) -> &'static str {
+    if SHEBANG_PREFIXES.iter().any(|prefix| text.starts_with(prefix)) {
+        return "text/x-shellscript";
+    }
+
+    let trimmed = text.trim_start_matches('\u{feff}').trim_start().to_ascii_lowercase();
+    if trimmed.starts_with("<svg") || (trimmed.starts_with("<?xml") && trimmed.contains("<svg")) {
+        "image/svg+xml"
+    } else if trimmed.starts_with("<!doctype html") || trimmed.starts_with("<html") || trimmed.starts_with("<script") {
+        "text/html"
+    } else if trimmed.starts_with("<?xml") {
+        "application/xml"
+    } else {
+        "text/plain"
+    }
+}
+
+fn normalize_mime(mime_type: &str) -> String {
+    let base = mime_type.split(';').next().unwrap_or("").trim().to_ascii_lowercase();
+    match base.as_str() {
+        "image/jpg" | "image/pjpeg" => "image/jpeg".to_string(),
+        "application/x-zip-compressed" => "application/zip".to_string(),
+        "application/x-gzip" => "application/gzip".to_string(),
+        "audio/x-wav" | "audio/wave" => "audio/wav".to_string(),
+        "audio/mp3" => "audio/mpeg".to_string(),
+        _ => base,
+    }
+}
+
+/// Whether a declared type is a plausible label for the detected content.
+///
+/// Container formats (ZIP, OLE, plain text) are shared by many concrete types,
+/// so the detected container only needs to be consistent with the declaration.
+fn is_compatible(declared: &str, detected: &str) -> bool {
+    if declared == detected || declared == MIME_OCTET_STREAM {
+        return true;
+    }
+
+    match detected {
+        "application/zip" => {
+            declared.starts_with("application/vnd.openxmlformats-officedocument")
+                || declared.starts_with("application/vnd.oasis.opendocument")
+                || matches!(declared, "application/epub+zip" | "application/java-archive")
+        }
+        "application/x-ole-storage" => matches!(
+            declared,
+            "application/msword" | "application/vnd.ms-excel" | "application/vnd.ms-powerpoint" | "application/vnd.ms-outlook"
+        ),
+        "text/plain" => {
+            (declared.starts_with("text/") && !matches!(declared, "text/html" | "text/x-shellscript"))
+                || matches!(declared, "application/json" | "application/xml" | "application/x-yaml" | "application/javascript")
+        }
+        "application/xml" => matches!(declared, "text/xml" | "application/xml") || declared.ends_with("+xml"),
+        "video/mp4" => matches!(declared, "audio/mp4" | "video/mp4" | "audio/x-m4a" | "video/x-m4v"),
+        "video/webm" => matches!(declared, "audio/webm" | "video/x-matroska"),
+        "audio/ogg" => matches!(declared, "video/ogg" | "application/ogg"),
+        _ => false,
+    }
+}
+
+/// Match a MIME type against a pattern that may use a `type/*` wildcard
+fn mime_matches(pattern: &str, mime_type: &str) -> bool {
+    let pattern = normalize_mime(pattern);
+    match pattern.strip_suffix("/*") {
+        Some(prefix) => mime_type.split('/').next() == Some(prefix),
+        None => pattern == "*/*" || pattern == mime_type,
+    }
+}
+
+fn file_extension(filename: &str) -> Option<String> {
+    let (stem, ext) = filename.rsplit_once('.')?;
+    if stem.is_empty() || ext.is_empty() {
+        return None;
+    }
+    Some(ext.to_ascii_lowercase())
+}
+
+/// Validate an upload against a tenant file type policy
+pub fn validate_file_type(
+    policy: &FileTypePolicy,
+    filename: &str,
+    declared_mime_type: &str,
+    data: &[u8],
+) -> Result<FileTypeValidation, FileTypeViolation> {
+    if let Some(extension) = file_extension(filename) {
+        let blocked = policy.blocked_extensions.iter()
+            .any(|blocked| blocked.trim_start_matches('.').eq_ignore_ascii_case(&extension));
+        if blocked {
+            return Err(FileTypeViolation::ExtensionBlocked { extension });
+        }
+    }
+
+    let declared = normalize_mime(declared_mime_type);
+    let detected = sniff_mime_type(data).map(str::to_string);
+
+    // Prefer the more specific declared type when it is consistent with a
+    // container match (e.g. a .docx detected as application/zip)
+    let effective = match &detected {
+        Some(detected) if declared != MIME_OCTET_STREAM && is_compatible(&declared, detected) => declared.clone(),
+        Some(detected) => detected.clone(),
+        None => declared.clone(),
+    };
+
+    for candidate in [&effective, &declared].into_iter().chain(detected.as_ref()) {
+        if policy.blocked_mime_types.iter().any(|pattern| mime_matches(pattern, candidate)) {
+            return Err(FileTypeViolation::TypeBlocked { mime_type: candidate.clone() });
+        }
+    }
+
+    if !policy.allowed_mime_types.is_empty()
+        && !policy.allowed_mime_types.iter().any(|pattern| mime_matches(pattern, &effective))
+    {
+        return Err(FileTypeViolation::TypeNotAllowed { mime_type: effective });
+    }
+
+    if policy.enforce_content_match {
+        if let Some(detected) = &detected {
+            if !is_compatible(&declared, detected) {
+                return Err(FileTypeViolation::ContentMismatch {
+                    declared,
+                    detected: detected.clone(),
+                });
+            }
+        }
+    }
+
+    Ok(FileTypeValidation {
+        declared_mime_type: declared,
+        detected_mime_type: detected,
+        effective_mime_type: effective,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use uuid::Uuid;
+
+    fn policy() -> FileTypePolicy {
+        FileTypePolicy::default_for_tenant(Uuid::new_v4())
+    }
+
+    #[test]
+    fn test_sniff_common_signatures() {
+        assert_eq!(sniff_mime_type(b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR"), Some("image/png"));
+        assert_eq!(sniff_mime_type(b"\xFF\xD8\xFF\xE0\0\x10JFIF"), Some("image/jpeg"));
+        assert_eq!(sniff_mime_type(b"%PDF-1.7\n"), Some("application/pdf"));
+        assert_eq!(sniff_mime_type(b"RIFF\0\0\0\0WEBPVP8 "), Some("image/webp"));
+        assert_eq!(sniff_mime_type(b"\0\0\0\x18ftypmp42"), Some("video/mp4"));
+        assert_eq!(sniff_mime_type(b"MZ\x90\0"), Some("application/x-msdownload"));
+        assert_eq!(sniff_mime_type(b"name,email\nada,ada@example.com\n"), Some("text/plain"));
+        assert_eq!(sniff_mime_type(b"<?xml version=\"1.0\"?><svg></svg>"), Some("image/svg+xml"));
+        assert_eq!(sniff_mime_type(b"\0\x01\x02\x03"), None);
+    }
+
+    #[test]
+    fn test_executable_disguised_as_image_is_rejected() {
+        let result = validate_file_type(&policy(), "holiday.png", "image/png", b"MZ\x90\0\x03\0\0\0");
+        assert_eq!(result.unwrap_err().code(), "FILE_TYPE_BLOCKED");
+    }
+
+    #[test]
+    fn test_blocked_extension() {
+        let result = validate_file_type(&policy(), "setup.EXE", "application/octet-stream", b"hello");
+        assert_eq!(
+            result.unwrap_err(),
+            FileTypeViolation::ExtensionBlocked { extension: "exe".to_string() }
+        );
+    }
+
+    #[test]
+    fn test_content_mismatch() {
+        let result = validate_file_type(&policy(), "report.pdf", "application/pdf", b"\x89PNG\r\n\x1a\n");
+        assert_eq!(result.unwrap_err().code(), "FILE_TYPE_MISMATCH");
+
+        let mut lenient = policy();
+        lenient.enforce_content_match = false;
+        let result = validate_file_type(&lenient, "report.pdf", "application/pdf", b"\x89PNG\r\n\x1a\n").unwrap();
+        assert_eq!(result.effective_mime_type, "image/png");
+    }
+
+    #[test]
+    fn test_container_formats_keep_declared_type() {
+        let docx = "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+        let result = validate_file_type(&policy(), "notes.docx", docx, b"PK\x03\x04\x14\0").unwrap();
+        assert_eq!(result.detected_mime_type.as_deref(), Some("application/zip"));
+        assert_eq!(result.effective_mime_type, docx);
+
+        let result = validate_file_type(&policy(), "data.json", "application/json", b"{\"a\": 1}").unwrap();
+        assert_eq!(result.effective_mime_type, "application/json");
+    }
+
+    #[test]
+    fn test_octet_stream_resolves_to_detected_type() {
+        let result = validate_file_type(&policy(), "photo", "application/octet-stream", b"\xFF\xD8\xFF\xE1").unwrap();
+        assert_eq!(result.effective_mime_type, "image/jpeg");
+    }
+
+    #[test]
+    fn test_allow_list_with_wildcards() {
+        let mut images_only = policy();
+        images_only.allowed_mime_types = vec!["image/*".to_string()];
+
+        assert!(validate_file_type(&images_only, "a.gif", "image/gif", b"GIF89a\x01\0").is_ok());
+        let result = validate_file_type(&images_only, "a.pdf", "application/pdf", b"%PDF-1.4");
+        assert_eq!(
+            result.unwrap_err(),
+            FileTypeViolation::TypeNotAllowed { mime_type: "application/pdf".to_string() }
+        );
+    }
+
+    #[test]
+    fn test_short_signatures_do_not_match_text() {
+        assert_eq!(sniff_mime_type(b"BM,Score\nada,12\n"), Some("text/plain"));
+        assert_eq!(sniff_mime_type(b"MZ-1142 shipped to Lyon\n"), Some("text/plain"));
+        assert_eq!(sniff_mime_type(b"ID3 tags are stripped on export\n"), Some("text/plain"));
+        assert_eq!(sniff_mime_type(b"#!important notes\n"), Some("text/plain"));
+
+        // The same prefixes on binary content still match
+        assert_eq!(sniff_mime_type(b"BM\x36\x00\x0c\x00"), Some("image/bmp"));
+        assert_eq!(sniff_mime_type(b"ID3\x04\x00\x00\x00\x00\x00"), Some("audio/mpeg"));
+        assert_eq!(sniff_mime_type(b"#!/bin/sh\nrm -rf /tmp/x\n"), Some("text/x-shellscript"));
+        assert_eq!(sniff_mime_type(b"#!/usr/bin/env python3\n"), Some("text/x-shellscript"));
+    }
+
+    #[test]
+    fn test_csv_starting_with_mz_is_accepted() {
+        let result = validate_file_type(&policy(), "parts.csv", "text/csv", b"MZ-1142,Widget\nMZ-1143,Gadget\n").unwrap();
+        assert_eq!(result.detected_mime_type.as_deref(), Some("text/plain"));
+        assert_eq!(result.effective_mime_type, "text/csv");
+    }
+}
@@ -29,6 +29,7 @@ impl FileWorker {
         let file_repo = Arc::new(PostgresFileRepository::new(self.pool.clone()));
         let permission_repo = Arc::new(PostgresFilePermissionRepository::new(self.pool.clone()));
         let share_repo = Arc::new(PostgresFileShareRepository::new(self.pool.clone()));
+        let policy_repo = Arc::new(PostgresFileTypePolicyRepository::new(self.pool.clone()));
 
         // Initialize storage manager
         let mut storage_manager = StorageManager::new();
@@ -50,6 +51,7 @@ impl FileWorker {
         let file_activities = Arc::new(FileActivitiesImpl::new(
             file_repo,
             permission_repo,
+            policy_repo,
             storage_manager,
         ));
 
@@ -0,0 +1,4 @@
+-- Monthly AI budget, enforced by ai-service against its usage records
+INSERT INTO quota_definitions (name, description, unit, category, free_limit, professional_limit, enterprise_limit) VALUES
+('ai_monthly_budget_cents', 'Monthly AI spend limit in cents', 'cents', 'ai', 500, 20000, -1)
+ON CONFLICT (name) DO NOTHING;
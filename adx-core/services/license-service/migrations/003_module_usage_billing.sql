@@ -0,0 +1,33 @@
+-- Usage charges of marketplace modules, reported hourly by module-service
+CREATE TABLE module_usage_charges (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL,
+    module_id VARCHAR(255) NOT NULL,
+    instance_id UUID NOT NULL,
+    developer_id VARCHAR(255) NOT NULL,
+    
+    -- Usage period and metric
+    period_start TIMESTAMPTZ NOT NULL,
+    period_end TIMESTAMPTZ NOT NULL,
+    metric VARCHAR(50) NOT NULL, -- 'cpu_seconds', 'memory_gb_hours', 'storage_gb_hours', 'api_calls'
+    quantity DECIMAL(20,6) NOT NULL,
+    unit_price DECIMAL(20,8) NOT NULL,
+    
+    -- Charge and revenue share
+    amount DECIMAL(20,8) NOT NULL,
+    developer_amount DECIMAL(20,8) NOT NULL,
+    platform_amount DECIMAL(20,8) NOT NULL,
+    currency VARCHAR(3) NOT NULL DEFAULT 'USD',
+    
+    -- Set once the charge is on a tenant invoice
+    invoice_number VARCHAR(100),
+    
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    
+    -- Module-service may report an hour more than once
+    CONSTRAINT uq_module_usage_charge UNIQUE (instance_id, period_start, metric),
+    CONSTRAINT fk_module_usage_charges_tenant FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_module_usage_charges_uninvoiced ON module_usage_charges(tenant_id) WHERE invoice_number IS NULL;
+CREATE INDEX idx_module_usage_charges_developer ON module_usage_charges(developer_id, period_start);
@@ -0,0 +1,35 @@
+-- Users holding a seat of the tenant's users_per_tenant quota
+CREATE TABLE seat_assignments (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    assigned_by UUID,
+    assigned_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT uq_seat_assignment UNIQUE (tenant_id, user_id),
+    CONSTRAINT fk_seat_assignments_tenant FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_seat_assignments_tenant ON seat_assignments(tenant_id);
+
+-- Outcome of the nightly seat reconciliation, one row per tenant and run
+CREATE TABLE seat_reconciliations (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL,
+
+    -- Seats counted from assignments against what the quota and Stripe held
+    assigned_seats BIGINT NOT NULL,
+    recorded_usage BIGINT NOT NULL,
+    billed_seats BIGINT, -- NULL when the tenant has no Stripe subscription
+
+    -- What the run corrected
+    usage_corrected BOOLEAN NOT NULL DEFAULT false,
+    billing_corrected BOOLEAN NOT NULL DEFAULT false,
+    error TEXT,
+
+    reconciled_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT fk_seat_reconciliations_tenant FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_seat_reconciliations_tenant ON seat_reconciliations(tenant_id, reconciled_at DESC);
@@ -0,0 +1,35 @@
+CREATE TYPE dunning_status AS ENUM ('active', 'paused', 'recovered', 'forgiven', 'suspended');
+
+-- A failed Stripe invoice being recovered, one row per invoice
+CREATE TABLE dunning_cases (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL,
+    license_id UUID NOT NULL,
+    stripe_invoice_id VARCHAR(255) NOT NULL,
+    amount_due DECIMAL(12,2) NOT NULL,
+    currency VARCHAR(3) NOT NULL,
+    status dunning_status NOT NULL DEFAULT 'active',
+
+    -- Retry schedule and how far the tenant has been restricted
+    attempt_count INTEGER NOT NULL DEFAULT 0,
+    next_attempt_at TIMESTAMPTZ,
+    last_error TEXT,
+    restricted_features TEXT[] NOT NULL DEFAULT '{}',
+
+    -- Operator actions
+    paused_by UUID,
+    paused_at TIMESTAMPTZ,
+    resolved_by UUID,
+    resolved_at TIMESTAMPTZ,
+    resolution_notes TEXT,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT uq_dunning_invoice UNIQUE (stripe_invoice_id),
+    CONSTRAINT fk_dunning_cases_tenant FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE,
+    CONSTRAINT fk_dunning_cases_license FOREIGN KEY (license_id) REFERENCES licenses(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_dunning_cases_tenant ON dunning_cases(tenant_id, created_at DESC);
+CREATE INDEX idx_dunning_cases_due ON dunning_cases(next_attempt_at) WHERE status = 'active';
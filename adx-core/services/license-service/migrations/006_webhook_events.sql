@@ -0,0 +1,21 @@
+CREATE TYPE webhook_event_status AS ENUM ('received', 'processed', 'ignored', 'failed');
+
+-- Payment provider webhook deliveries, kept so redelivered events are
+-- applied once and failed ones can be replayed
+CREATE TABLE webhook_events (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    provider VARCHAR(20) NOT NULL CHECK (provider IN ('stripe', 'paypal')),
+    event_id VARCHAR(255) NOT NULL,
+    event_type VARCHAR(100) NOT NULL,
+    payload JSONB NOT NULL,
+    status webhook_event_status NOT NULL DEFAULT 'received',
+    attempts INTEGER NOT NULL DEFAULT 1,
+    error TEXT,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    processed_at TIMESTAMPTZ,
+
+    CONSTRAINT uq_webhook_event UNIQUE (provider, event_id)
+);
+
+CREATE INDEX idx_webhook_events_status ON webhook_events(status, received_at DESC);
+CREATE INDEX idx_webhook_events_type ON webhook_events(provider, event_type);
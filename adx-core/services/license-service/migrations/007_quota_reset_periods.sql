@@ -0,0 +1,36 @@
+CREATE TYPE quota_reset_period AS ENUM ('never', 'hourly', 'daily', 'monthly');
+
+-- Counters reset at the start of each period; 'never' quotas are allocations
+-- that go up and down as resources are created and removed
+ALTER TABLE quota_definitions
+    ADD COLUMN reset_period quota_reset_period NOT NULL DEFAULT 'never';
+
+UPDATE quota_definitions SET reset_period = 'hourly'
+WHERE name IN ('api_calls_per_hour', 'workflow_executions_per_hour');
+
+UPDATE quota_definitions SET reset_period = 'daily'
+WHERE name = 'api_calls_per_day';
+
+UPDATE quota_definitions SET reset_period = 'monthly'
+WHERE name = 'ai_monthly_budget_cents';
+
+-- AI requests, counted by ai-service
+INSERT INTO quota_definitions (name, description, unit, category, reset_period, free_limit, professional_limit, enterprise_limit) VALUES
+('ai_requests_per_day', 'AI requests per day', 'requests', 'ai', 'daily', 100, 5000, -1)
+ON CONFLICT (name) DO NOTHING;
+
+-- Give tenants that already hold a license the new quota at their tier's limit
+INSERT INTO tenant_quotas (tenant_id, quota_definition_id, quota_limit)
+SELECT DISTINCT ON (l.tenant_id)
+    l.tenant_id,
+    qd.id,
+    CASE l.subscription_tier
+        WHEN 'free' THEN qd.free_limit
+        WHEN 'professional' THEN qd.professional_limit
+        ELSE qd.enterprise_limit
+    END
+FROM licenses l
+CROSS JOIN quota_definitions qd
+WHERE qd.name = 'ai_requests_per_day'
+ORDER BY l.tenant_id, l.created_at DESC
+ON CONFLICT (tenant_id, quota_definition_id) DO NOTHING;
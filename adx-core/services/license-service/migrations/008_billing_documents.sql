@@ -0,0 +1,25 @@
+CREATE TYPE billing_document_type AS ENUM ('invoice', 'receipt');
+
+-- Invoice and receipt PDFs of billing records, stored in file-service
+CREATE TABLE billing_documents (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL,
+    billing_history_id UUID NOT NULL,
+    document_type billing_document_type NOT NULL,
+    document_number VARCHAR(100) NOT NULL,
+
+    -- The PDF in file-service
+    file_id UUID NOT NULL,
+    filename VARCHAR(255) NOT NULL,
+    file_size BIGINT NOT NULL,
+
+    amount DECIMAL(10,2) NOT NULL,
+    currency VARCHAR(3) NOT NULL,
+    issued_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT uq_billing_document UNIQUE (billing_history_id, document_type),
+    CONSTRAINT fk_billing_documents_tenant FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE,
+    CONSTRAINT fk_billing_documents_history FOREIGN KEY (billing_history_id) REFERENCES billing_history(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_billing_documents_tenant ON billing_documents(tenant_id, issued_at DESC);
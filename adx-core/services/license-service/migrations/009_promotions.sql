@@ -0,0 +1,72 @@
+CREATE TYPE coupon_discount_type AS ENUM ('percentage', 'fixed');
+CREATE TYPE credit_entry_type AS ENUM ('grant', 'applied', 'reversal');
+
+-- Promotional codes tenants can redeem when subscribing
+CREATE TABLE coupons (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    code VARCHAR(50) NOT NULL,
+    description TEXT,
+    discount_type coupon_discount_type NOT NULL,
+
+    -- Percent off for percentage coupons, amount off for fixed ones
+    discount_value DECIMAL(10,2) NOT NULL,
+    currency VARCHAR(3),
+
+    -- Tiers the coupon can be used with; any tier when empty
+    eligible_tiers TEXT[] NOT NULL DEFAULT '{}',
+
+    -- Redemption limits; NULL max_redemptions is unlimited
+    max_redemptions INTEGER,
+    max_redemptions_per_tenant INTEGER NOT NULL DEFAULT 1,
+    redemption_count INTEGER NOT NULL DEFAULT 0,
+
+    valid_from TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    valid_until TIMESTAMPTZ,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+
+    created_by UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT uq_coupon_code UNIQUE (code),
+    CONSTRAINT chk_coupon_discount CHECK (
+        discount_value > 0 AND (discount_type = 'fixed' OR discount_value <= 100)
+    ),
+    CONSTRAINT chk_coupon_currency CHECK (discount_type = 'percentage' OR currency IS NOT NULL)
+);
+
+-- Each use of a coupon, kept as the audit trail of discounts given
+CREATE TABLE coupon_redemptions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    coupon_id UUID NOT NULL,
+    tenant_id UUID NOT NULL,
+    license_id UUID,
+    original_amount DECIMAL(10,2) NOT NULL,
+    discount_amount DECIMAL(10,2) NOT NULL,
+    currency VARCHAR(3) NOT NULL,
+    redeemed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT fk_coupon_redemptions_coupon FOREIGN KEY (coupon_id) REFERENCES coupons(id),
+    CONSTRAINT fk_coupon_redemptions_tenant FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_coupon_redemptions_coupon ON coupon_redemptions(coupon_id, tenant_id);
+
+-- Ledger of tenant credit. Grants are positive, credit used on charges is
+-- negative, and the balance is the sum per currency.
+CREATE TABLE tenant_credit_entries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id UUID NOT NULL,
+    entry_type credit_entry_type NOT NULL,
+    amount DECIMAL(12,2) NOT NULL,
+    currency VARCHAR(3) NOT NULL,
+    reason TEXT NOT NULL,
+    -- The charge credit was applied to, or the entry a reversal undoes
+    reference VARCHAR(255),
+    created_by UUID,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT fk_tenant_credit_entries_tenant FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_tenant_credit_entries_tenant ON tenant_credit_entries(tenant_id, currency, created_at DESC);
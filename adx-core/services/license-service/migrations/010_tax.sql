@@ -0,0 +1,63 @@
+CREATE TYPE tax_id_status AS ENUM ('unverified', 'valid', 'invalid');
+
+-- Where a tenant is billed, and its tax registration
+CREATE TABLE tenant_tax_profiles (
+    tenant_id UUID PRIMARY KEY,
+    legal_name VARCHAR(255),
+    country CHAR(2) NOT NULL,
+    region VARCHAR(10),
+    postal_code VARCHAR(20),
+
+    -- VAT/GST registration number, including its country prefix
+    tax_id VARCHAR(32),
+    tax_id_status tax_id_status NOT NULL DEFAULT 'unverified',
+    tax_id_checked_at TIMESTAMPTZ,
+    tax_exempt BOOLEAN NOT NULL DEFAULT FALSE,
+
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT fk_tenant_tax_profiles_tenant FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE
+);
+
+-- Tax tables of the manual tax provider. Rates of a region apply on top
+-- of the rates of its country.
+CREATE TABLE tax_rates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    country CHAR(2) NOT NULL,
+    region VARCHAR(10),
+    tax_type VARCHAR(20) NOT NULL, -- 'vat', 'gst', 'sales_tax'
+    name VARCHAR(100) NOT NULL,
+    -- Percent, e.g. 19.0000
+    rate DECIMAL(7,4) NOT NULL,
+    active BOOLEAN NOT NULL DEFAULT TRUE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT chk_tax_rate CHECK (rate >= 0 AND rate <= 100)
+);
+
+CREATE UNIQUE INDEX uq_tax_rates_jurisdiction ON tax_rates(country, COALESCE(region, ''), tax_type) WHERE active;
+
+-- Tax charged on each invoice line, by jurisdiction
+CREATE TABLE invoice_tax_lines (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    invoice_number VARCHAR(100) NOT NULL,
+    tenant_id UUID NOT NULL,
+    line_index INTEGER NOT NULL,
+    description TEXT NOT NULL,
+    taxable_amount DECIMAL(12,2) NOT NULL,
+    tax_type VARCHAR(20) NOT NULL,
+    jurisdiction VARCHAR(100) NOT NULL,
+    rate DECIMAL(7,4) NOT NULL,
+    tax_amount DECIMAL(12,2) NOT NULL,
+    currency VARCHAR(3) NOT NULL,
+    provider VARCHAR(20) NOT NULL,
+    -- VAT owed by the customer rather than charged
+    reverse_charge BOOLEAN NOT NULL DEFAULT FALSE,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT fk_invoice_tax_lines_tenant FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE
+);
+
+CREATE INDEX idx_invoice_tax_lines_invoice ON invoice_tax_lines(invoice_number, line_index);
@@ -0,0 +1,67 @@
+-- Plan prices, per currency
+CREATE TABLE price_book_entries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    currency CHAR(3) NOT NULL,
+    subscription_tier subscription_tier NOT NULL,
+    billing_cycle billing_cycle NOT NULL,
+    price DECIMAL(10,2) NOT NULL,
+    setup_fee DECIMAL(10,2) NOT NULL DEFAULT 0,
+    -- Stripe price subscriptions in this currency are created with
+    stripe_price_id VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT uq_price_book_entry UNIQUE (currency, subscription_tier, billing_cycle),
+    CONSTRAINT chk_price_book_amounts CHECK (price >= 0 AND setup_fee >= 0)
+);
+
+-- The prices that were built in until now
+INSERT INTO price_book_entries (currency, subscription_tier, billing_cycle, price, setup_fee, stripe_price_id) VALUES
+    ('USD', 'free', 'monthly', 0.00, 0.00, NULL),
+    ('USD', 'free', 'yearly', 0.00, 0.00, NULL),
+    ('USD', 'professional', 'monthly', 29.00, 0.00, 'price_professional_monthly'),
+    ('USD', 'professional', 'yearly', 290.00, 0.00, 'price_professional_yearly'),
+    ('USD', 'enterprise', 'monthly', 99.00, 99.00, 'price_enterprise_monthly'),
+    ('USD', 'enterprise', 'yearly', 990.00, 99.00, 'price_enterprise_yearly');
+
+-- Exchange rates, kept as a history: one unit of base_currency is worth
+-- `rate` units of quote_currency
+CREATE TABLE exchange_rates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    base_currency CHAR(3) NOT NULL,
+    quote_currency CHAR(3) NOT NULL,
+    rate DECIMAL(18,8) NOT NULL,
+    source VARCHAR(50) NOT NULL,
+    effective_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT chk_exchange_rate CHECK (rate > 0)
+);
+
+CREATE INDEX idx_exchange_rates_pair ON exchange_rates(base_currency, quote_currency, effective_at DESC);
+
+-- Currency each tenant is priced and billed in
+CREATE TABLE tenant_billing_currencies (
+    tenant_id UUID PRIMARY KEY,
+    currency CHAR(3) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT fk_tenant_billing_currencies_tenant FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE
+);
+
+-- Rate between the default currency and an invoice's currency when the
+-- invoice was generated, and the invoice total in the default currency
+CREATE TABLE invoice_exchange_rates (
+    invoice_number VARCHAR(100) PRIMARY KEY,
+    tenant_id UUID NOT NULL,
+    base_currency CHAR(3) NOT NULL,
+    invoice_currency CHAR(3) NOT NULL,
+    rate DECIMAL(18,8) NOT NULL,
+    source VARCHAR(50) NOT NULL,
+    rate_effective_at TIMESTAMPTZ NOT NULL,
+    base_amount DECIMAL(12,2) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT fk_invoice_exchange_rates_tenant FOREIGN KEY (tenant_id) REFERENCES tenants(id) ON DELETE CASCADE
+);
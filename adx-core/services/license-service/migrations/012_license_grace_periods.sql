@@ -0,0 +1,6 @@
+-- When a license last stopped being active. Lapsed tenants keep read-only
+-- access for the configured grace period counted from here.
+ALTER TABLE licenses ADD COLUMN lapsed_at TIMESTAMPTZ;
+
+UPDATE licenses SET lapsed_at = updated_at
+WHERE status IN ('expired', 'suspended', 'cancelled');
@@ -0,0 +1,24 @@
+-- API rate limit tiers
+-- The API gateway holds each tenant to its plan's request rate, alongside
+-- the existing hourly call and concurrent workflow quotas. These are rates
+-- the gateway counts itself, so they never reset here.
+INSERT INTO quota_definitions (name, description, unit, category, reset_period, free_limit, professional_limit, enterprise_limit) VALUES
+('api_requests_per_minute', 'API requests per minute, enforced by the API gateway', 'requests', 'api', 'never', 60, 600, 6000),
+('api_burst_requests', 'API requests per 10 seconds, enforced by the API gateway', 'requests', 'api', 'never', 20, 150, 1500)
+ON CONFLICT (name) DO NOTHING;
+
+-- Give tenants that already hold a license the new quotas at their tier's limit
+INSERT INTO tenant_quotas (tenant_id, quota_definition_id, quota_limit)
+SELECT DISTINCT ON (l.tenant_id, qd.id)
+    l.tenant_id,
+    qd.id,
+    CASE l.subscription_tier
+        WHEN 'free' THEN qd.free_limit
+        WHEN 'professional' THEN qd.professional_limit
+        ELSE qd.enterprise_limit
+    END
+FROM licenses l
+CROSS JOIN quota_definitions qd
+WHERE qd.name IN ('api_requests_per_minute', 'api_burst_requests')
+ORDER BY l.tenant_id, qd.id, l.created_at DESC
+ON CONFLICT (tenant_id, quota_definition_id) DO NOTHING;
@@ -1,13 +1,19 @@
-use chrono::{DateTime, Utc, Duration};
+use adx_shared::email::{EmailRecipient, SendEmailRequest, SystemEmail};
+use chrono::{DateTime, Utc, Duration, TimeZone};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
     billing::{BillingService, PaymentResult},
+    config::{DunningConfig, GraceConfig},
     error::{LicenseError, Result},
     models::*,
-    repositories::{LicenseRepository, QuotaRepository, BillingRepository, ComplianceRepository},
+    notifications::EmailClient,
+    pricing,
+    promotions,
+    repositories::{LicenseRepository, QuotaRepository, BillingRepository, ComplianceRepository, SeatRepository, DunningRepository, WebhookEventRepository, PromotionRepository, PricingRepository},
+    tenants::TenantServiceClient,
 };
 
 // Activity request/response types
@@ -78,6 +84,29 @@ pub struct ProcessPaymentRequest {
     pub invoice_id: Option<String>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RedeemCouponRequest {
+    pub tenant_id: Uuid,
+    pub license_id: Uuid,
+    pub coupon_code: String,
+    pub subscription_tier: SubscriptionTier,
+    pub amount: Decimal,
+    pub currency: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RevertCouponRedemptionRequest {
+    pub redemption_id: Uuid,
+    pub reason: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct QuotePlanPriceRequest {
+    pub tenant_id: Uuid,
+    pub subscription_tier: SubscriptionTier,
+    pub billing_cycle: BillingCycle,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GenerateComplianceReportRequest {
     pub tenant_id: Uuid,
@@ -86,6 +115,88 @@ pub struct GenerateComplianceReportRequest {
     pub include_recommendations: bool,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReconcileTenantSeatsRequest {
+    pub tenant_id: Uuid,
+    pub adjust_billing: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ApplySubscriptionChangeRequest {
+    pub preview: ProrationPreview,
+}
+
+/// What a plan change replaced, kept so the change can be reverted
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AppliedSubscriptionChange {
+    pub license_id: Uuid,
+    pub tenant_id: Uuid,
+    pub previous_tier: SubscriptionTier,
+    pub previous_billing_cycle: BillingCycle,
+    pub previous_price: Decimal,
+    pub previous_expires_at: Option<DateTime<Utc>>,
+    pub quota_changes: Vec<QuotaLimitChange>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateTenantPlanRequest {
+    pub tenant_id: Uuid,
+    pub subscription_tier: SubscriptionTier,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CaptureProrationPaymentRequest {
+    pub preview: ProrationPreview,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CaptureProrationPaymentResult {
+    pub payment: Option<PaymentResult>,
+    pub invoice_number: Option<String>,
+    /// Ledger entry for a change that leaves a credit
+    pub credit: Option<CreditEntry>,
+}
+
+/// A Stripe invoice whose payment failed, from the webhook
+#[derive(Debug, Serialize, Deserialize)]
+pub struct FailedInvoice {
+    pub stripe_invoice_id: String,
+    pub stripe_customer_id: String,
+    pub amount_due: Decimal,
+    pub currency: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DunningCaseRequest {
+    pub case_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DunningChargeResult {
+    pub case: DunningCase,
+    pub paid: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResolveDunningCaseRequest {
+    pub case_id: Uuid,
+    pub status: DunningStatus,
+    pub resolved_by: Option<Uuid>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WebhookEventRequest {
+    pub webhook_event_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MarkWebhookEventRequest {
+    pub webhook_event_id: Uuid,
+    pub status: WebhookEventStatus,
+    pub error: Option<String>,
+}
+
 // License Activities
 #[derive(Clone)]
 pub struct LicenseActivities {
@@ -93,7 +204,16 @@ pub struct LicenseActivities {
     quota_repo: QuotaRepository,
     billing_repo: BillingRepository,
     compliance_repo: ComplianceRepository,
+    seat_repo: SeatRepository,
+    dunning_repo: DunningRepository,
+    webhook_repo: WebhookEventRepository,
+    promotion_repo: PromotionRepository,
+    pricing_repo: PricingRepository,
     billing_service: BillingService,
+    tenant_client: TenantServiceClient,
+    email_client: EmailClient,
+    dunning_config: DunningConfig,
+    grace_config: GraceConfig,
 }
 
 impl LicenseActivities {
@@ -102,14 +222,32 @@ impl LicenseActivities {
         quota_repo: QuotaRepository,
         billing_repo: BillingRepository,
         compliance_repo: ComplianceRepository,
+        seat_repo: SeatRepository,
+        dunning_repo: DunningRepository,
+        webhook_repo: WebhookEventRepository,
+        promotion_repo: PromotionRepository,
+        pricing_repo: PricingRepository,
         billing_service: BillingService,
+        tenant_client: TenantServiceClient,
+        email_client: EmailClient,
+        dunning_config: DunningConfig,
+        grace_config: GraceConfig,
     ) -> Self {
         Self {
             license_repo,
             quota_repo,
             billing_repo,
             compliance_repo,
+            seat_repo,
+            dunning_repo,
+            webhook_repo,
+            promotion_repo,
+            pricing_repo,
             billing_service,
+            tenant_client,
+            email_client,
+            dunning_config,
+            grace_config,
         }
     }
 
@@ -128,13 +266,15 @@ impl LicenseActivities {
             None
         };
 
-        // Create license
+        // Create license, priced in the tenant's currency
+        let currency = self.tenant_currency(request.tenant_id).await?;
+        let price = self.plan_price(&request.subscription_tier, &request.billing_cycle, &currency).await?;
         let license_request = CreateLicenseRequest {
             tenant_id: request.tenant_id,
             subscription_tier: request.subscription_tier.clone(),
             billing_cycle: request.billing_cycle.clone(),
-            base_price: self.get_tier_price(&request.subscription_tier, &request.billing_cycle),
-            currency: "USD".to_string(),
+            base_price: price.price,
+            currency: price.currency.clone(),
             features: request.features,
             custom_quotas: request.custom_quotas,
             auto_renew: true,
@@ -147,7 +287,7 @@ impl LicenseActivities {
 
         // Create subscription if using payment provider
         let subscription_id = if let Some(ref customer_id) = customer_id {
-            let price_id = self.get_price_id(&request.subscription_tier, &request.billing_cycle);
+            let price_id = self.stripe_price_id(&price);
             Some(self.billing_service.create_subscription(
                 customer_id,
                 &price_id,
@@ -161,6 +301,7 @@ impl LicenseActivities {
         if customer_id.is_some() || subscription_id.is_some() {
             let update_request = UpdateLicenseRequest {
                 subscription_tier: None,
+                billing_cycle: None,
                 status: Some(LicenseStatus::Active),
                 base_price: None,
                 expires_at: None,
@@ -344,6 +485,7 @@ impl LicenseActivities {
         // Update license
         let update_request = UpdateLicenseRequest {
             subscription_tier: None,
+            billing_cycle: None,
             status: Some(LicenseStatus::Active),
             base_price: None,
             expires_at: new_expires_at,
@@ -387,11 +529,36 @@ impl LicenseActivities {
 
     // Payment processing activity
     pub async fn process_payment(&self, request: ProcessPaymentRequest) -> Result<PaymentResult> {
-        let payment_result = self.billing_service.process_payment(
+        // The tenant's credit is used before the customer is charged
+        let credit = self.promotion_repo.apply_credit(
+            request.tenant_id,
             request.amount,
             &request.currency,
-            &request.customer_id,
+            request.invoice_id.as_deref(),
         ).await?;
+        let credits_applied = credit.as_ref().map(|entry| -entry.amount).unwrap_or(Decimal::ZERO);
+        let amount_due = request.amount - credits_applied;
+
+        let payment_result = match &credit {
+            Some(entry) if amount_due <= Decimal::ZERO => PaymentResult {
+                payment_id: format!("credit_{}", entry.id),
+                status: PaymentStatus::Completed,
+                amount: Decimal::ZERO,
+                currency: request.currency.clone(),
+                client_secret: None,
+            },
+            _ => match self.billing_service.process_payment(amount_due, &request.currency, &request.customer_id).await {
+                Ok(payment_result) => payment_result,
+                Err(e) => {
+                    if let Some(entry) = &credit {
+                        if let Err(reverse_error) = self.promotion_repo.reverse_credit(entry, "Charge failed").await {
+                            tracing::error!("Failed to return credit {} after a failed charge: {}", entry.id, reverse_error);
+                        }
+                    }
+                    return Err(e);
+                }
+            },
+        };
 
         // Log compliance event
         let compliance_log = ComplianceLog {
@@ -404,6 +571,8 @@ impl LicenseActivities {
             details: Some(serde_json::json!({
                 "payment_id": payment_result.payment_id,
                 "amount": request.amount,
+                "credits_applied": credits_applied,
+                "amount_charged": payment_result.amount,
                 "currency": request.currency,
                 "status": payment_result.status,
                 "payment_method": request.payment_method
@@ -422,6 +591,83 @@ impl LicenseActivities {
         Ok(payment_result)
     }
 
+    // Promotion activities
+    /// What the tenant would pay for a charge after the coupon and their
+    /// credit. Fails when the coupon can't be used.
+    pub async fn validate_promotion(&self, request: ValidatePromotionRequest) -> Result<PromotionQuote> {
+        let mut discount_amount = Decimal::ZERO;
+        let coupon_code = match &request.coupon_code {
+            Some(code) => {
+                let coupon = self.redeemable_coupon(request.tenant_id, code, &request.subscription_tier, &request.currency).await?;
+                discount_amount = promotions::discount_for(&coupon, request.amount);
+                Some(coupon.code)
+            }
+            None => None,
+        };
+
+        let discounted = (request.amount - discount_amount).max(Decimal::ZERO);
+        let balance = self.promotion_repo.credit_balance(request.tenant_id, &request.currency).await?;
+        let credits_applied = balance.max(Decimal::ZERO).min(discounted);
+
+        Ok(PromotionQuote {
+            coupon_code,
+            original_amount: request.amount,
+            discount_amount,
+            credits_applied,
+            amount_due: discounted - credits_applied,
+            currency: request.currency,
+        })
+    }
+
+    pub async fn redeem_coupon(&self, request: RedeemCouponRequest) -> Result<CouponRedemption> {
+        let coupon = self.redeemable_coupon(request.tenant_id, &request.coupon_code, &request.subscription_tier, &request.currency).await?;
+        let discount_amount = promotions::discount_for(&coupon, request.amount);
+
+        let redemption = self.promotion_repo.redeem(
+            coupon.id,
+            request.tenant_id,
+            Some(request.license_id),
+            request.amount,
+            discount_amount,
+            &request.currency,
+        ).await?
+            .ok_or_else(|| LicenseError::InvalidCoupon(format!("{}: the coupon was used up before it could be redeemed", coupon.code)))?;
+
+        self.log_promotion_event(
+            request.tenant_id,
+            "coupon_redeemed",
+            "info",
+            format!("Coupon {} redeemed: {} {} off", coupon.code, discount_amount, request.currency),
+            serde_json::json!({
+                "coupon_id": coupon.id,
+                "coupon_code": coupon.code,
+                "redemption_id": redemption.id,
+                "license_id": request.license_id,
+                "original_amount": request.amount,
+                "discount_amount": discount_amount,
+                "currency": request.currency
+            }),
+            redemption.id,
+        ).await?;
+
+        Ok(redemption)
+    }
+
+    /// Give a redemption back to the coupon when what it discounted fell through
+    pub async fn revert_coupon_redemption(&self, request: RevertCouponRedemptionRequest) -> Result<()> {
+        if let Some(redemption) = self.promotion_repo.revert_redemption(request.redemption_id).await? {
+            self.log_promotion_event(
+                redemption.tenant_id,
+                "coupon_redemption_reverted",
+                "warning",
+                format!("Coupon redemption reverted: {}", request.reason),
+                serde_json::to_value(&redemption)?,
+                redemption.id,
+            ).await?;
+        }
+        Ok(())
+    }
+
     // Compliance reporting activity
     pub async fn generate_compliance_report(&self, request: GenerateComplianceReportRequest) -> Result<ComplianceReport> {
         // Get license status
@@ -506,18 +752,895 @@ impl LicenseActivities {
         })
     }
 
+    // Subscription change activities
+    pub async fn preview_subscription_change(&self, request: SubscriptionChangeRequest) -> Result<ProrationPreview> {
+        let license = self.license_repo.get_by_tenant_id(request.tenant_id).await?
+            .ok_or_else(|| LicenseError::LicenseNotFound(request.tenant_id.to_string()))?;
+        if !license.is_active() {
+            return Err(LicenseError::ValidationError(format!("License {} is not active", license.id)));
+        }
+
+        let new_billing_cycle = request.new_billing_cycle.unwrap_or(license.billing_cycle.clone());
+        if request.new_tier == license.subscription_tier && new_billing_cycle == license.billing_cycle {
+            return Err(LicenseError::ValidationError("Subscription is already on this plan".to_string()));
+        }
+        let new_price = self.plan_price(&request.new_tier, &new_billing_cycle, &license.currency).await?.price;
+        let proration = pricing::prorate(
+            license.base_price,
+            &license.billing_cycle,
+            license.expires_at,
+            new_price,
+            &new_billing_cycle,
+            &license.currency,
+            Utc::now(),
+        );
+
+        let quota_changes = self.quota_repo.get_tier_limit_changes(request.tenant_id, &request.new_tier).await?;
+        let blocked_reason = quota_changes.iter()
+            .find(|change| change.quota_name == SEAT_QUOTA && change.new_limit >= 0 && change.current_usage > change.new_limit)
+            .map(|change| format!(
+                "{} seats are assigned but the {:?} plan has {}; free seats before changing plan",
+                change.current_usage, request.new_tier, change.new_limit
+            ));
+
+        Ok(ProrationPreview {
+            id: Uuid::new_v4(),
+            license_id: license.id,
+            tenant_id: request.tenant_id,
+            current_tier: license.subscription_tier,
+            new_tier: request.new_tier,
+            current_billing_cycle: license.billing_cycle,
+            new_billing_cycle,
+            current_price: license.base_price,
+            new_price,
+            days_remaining: proration.days_remaining,
+            unused_credit: proration.unused_credit,
+            new_charge: proration.new_charge,
+            amount_due: proration.amount_due(),
+            currency: license.currency,
+            new_period_end: proration.new_period_end,
+            quota_changes,
+            blocked_reason,
+        })
+    }
+
+    pub async fn apply_subscription_change(&self, request: ApplySubscriptionChangeRequest) -> Result<AppliedSubscriptionChange> {
+        let preview = request.preview;
+        let license = self.license_repo.get_by_id(preview.license_id).await?
+            .ok_or_else(|| LicenseError::LicenseNotFound(preview.license_id.to_string()))?;
+
+        let update_request = UpdateLicenseRequest {
+            subscription_tier: Some(preview.new_tier.clone()),
+            billing_cycle: Some(preview.new_billing_cycle.clone()),
+            status: None,
+            base_price: Some(preview.new_price),
+            expires_at: preview.new_period_end,
+            auto_renew: None,
+            features: None,
+            custom_quotas: None,
+        };
+        self.license_repo.update(license.id, update_request).await?;
+
+        let limits: Vec<(String, i64)> = preview.quota_changes.iter()
+            .map(|change| (change.quota_name.clone(), change.new_limit))
+            .collect();
+        self.quota_repo.set_quota_limits(preview.tenant_id, &limits).await?;
+
+        Ok(AppliedSubscriptionChange {
+            license_id: license.id,
+            tenant_id: license.tenant_id,
+            previous_tier: license.subscription_tier,
+            previous_billing_cycle: license.billing_cycle,
+            previous_price: license.base_price,
+            previous_expires_at: license.expires_at,
+            quota_changes: preview.quota_changes,
+        })
+    }
+
+    pub async fn revert_subscription_change(&self, applied: AppliedSubscriptionChange) -> Result<()> {
+        let update_request = UpdateLicenseRequest {
+            subscription_tier: Some(applied.previous_tier),
+            billing_cycle: Some(applied.previous_billing_cycle),
+            status: None,
+            base_price: Some(applied.previous_price),
+            expires_at: applied.previous_expires_at,
+            auto_renew: None,
+            features: None,
+            custom_quotas: None,
+        };
+        self.license_repo.update(applied.license_id, update_request).await?;
+
+        let limits: Vec<(String, i64)> = applied.quota_changes.iter()
+            .map(|change| (change.quota_name.clone(), change.current_limit))
+            .collect();
+        self.quota_repo.set_quota_limits(applied.tenant_id, &limits).await
+    }
+
+    pub async fn update_tenant_plan(&self, request: UpdateTenantPlanRequest) -> Result<()> {
+        self.tenant_client.update_plan(request.tenant_id, &request.subscription_tier).await
+    }
+
+    /// Charge what the plan change costs. A change that leaves a credit
+    /// adds it to the tenant's credit ledger, where the next charges use
+    /// it; one that costs nothing does neither.
+    pub async fn capture_proration_payment(&self, request: CaptureProrationPaymentRequest) -> Result<CaptureProrationPaymentResult> {
+        let preview = request.preview;
+        if preview.amount_due < Decimal::ZERO {
+            let credit = self.credit_plan_change(&preview).await?;
+            return Ok(CaptureProrationPaymentResult { payment: None, invoice_number: None, credit: Some(credit) });
+        }
+        if preview.amount_due.is_zero() {
+            return Ok(CaptureProrationPaymentResult { payment: None, invoice_number: None, credit: None });
+        }
+
+        let license = self.license_repo.get_by_id(preview.license_id).await?
+            .ok_or_else(|| LicenseError::LicenseNotFound(preview.license_id.to_string()))?;
+        let customer_id = license.stripe_customer_id.as_deref()
+            .ok_or_else(|| LicenseError::PaymentError(format!("License {} has no payment method to charge", license.id)))?;
+
+        let payment = self.billing_service.process_payment(preview.amount_due, &preview.currency, customer_id).await?;
+        if !matches!(payment.status, PaymentStatus::Completed) {
+            return Err(LicenseError::PaymentError(format!(
+                "Payment {} for the plan change was not completed: {:?}",
+                payment.payment_id, payment.status
+            )));
+        }
+
+        let now = Utc::now();
+        let invoice_number = self.billing_service.generate_invoice_number().await;
+        self.billing_repo.create_billing_record(BillingHistory {
+            id: Uuid::new_v4(),
+            tenant_id: preview.tenant_id,
+            license_id: preview.license_id,
+            invoice_number: invoice_number.clone(),
+            amount: preview.amount_due,
+            currency: preview.currency.clone(),
+            tax_amount: Decimal::ZERO,
+            billing_period_start: now,
+            billing_period_end: preview.new_period_end.or(license.expires_at).unwrap_or(now),
+            payment_status: PaymentStatus::Completed,
+            payment_method: Some("stripe".to_string()),
+            payment_reference: Some(payment.payment_id.clone()),
+            paid_at: Some(now),
+            usage_details: Some(serde_json::json!({
+                "type": "plan_change_proration",
+                "from_tier": preview.current_tier,
+                "to_tier": preview.new_tier,
+                "unused_credit": preview.unused_credit,
+                "new_charge": preview.new_charge
+            })),
+            created_at: now,
+            updated_at: now,
+        }).await?;
+
+        Ok(CaptureProrationPaymentResult {
+            payment: Some(payment),
+            invoice_number: Some(invoice_number),
+            credit: None,
+        })
+    }
+
+    /// Credit what a plan change leaves over. Keyed on the preview so a
+    /// retried capture credits the change once.
+    async fn credit_plan_change(&self, preview: &ProrationPreview) -> Result<CreditEntry> {
+        let grant = GrantCreditRequest {
+            tenant_id: preview.tenant_id,
+            amount: -preview.amount_due,
+            currency: preview.currency.clone(),
+            reason: format!("Unused time on the {:?} plan", preview.current_tier),
+            granted_by: None,
+        };
+        let reference = format!("plan-change:{}", preview.id);
+        let entry = self.promotion_repo.grant_credit_once(&grant, &reference).await?;
+
+        self.log_promotion_event(
+            preview.tenant_id,
+            "credit_granted",
+            "info",
+            format!("Plan change credit: {} {}", entry.amount, entry.currency),
+            serde_json::json!({
+                "credit_entry_id": entry.id,
+                "license_id": preview.license_id,
+                "from_tier": preview.current_tier,
+                "to_tier": preview.new_tier,
+                "unused_credit": preview.unused_credit,
+                "new_charge": preview.new_charge
+            }),
+            entry.id,
+        ).await?;
+
+        Ok(entry)
+    }
+
+    pub async fn update_stripe_subscription_plan(&self, request: UpdateTenantPlanRequest) -> Result<()> {
+        let license = self.license_repo.get_by_tenant_id(request.tenant_id).await?
+            .ok_or_else(|| LicenseError::LicenseNotFound(request.tenant_id.to_string()))?;
+        let Some(subscription_id) = license.stripe_subscription_id.as_deref() else {
+            return Ok(());
+        };
+
+        let price = self.plan_price(&license.subscription_tier, &license.billing_cycle, &license.currency).await?;
+        let price_id = self.stripe_price_id(&price);
+        self.billing_service.update_subscription_price(subscription_id, &price_id).await
+    }
+
+    // Seat reconciliation activities
+    pub async fn list_seat_tenants(&self) -> Result<Vec<Uuid>> {
+        self.seat_repo.get_tenants_with_seats().await
+    }
+
+    pub async fn reconcile_tenant_seats(&self, request: ReconcileTenantSeatsRequest) -> Result<SeatReconciliation> {
+        let assigned_seats = self.seat_repo.count(request.tenant_id).await?;
+        let quota = self.quota_repo.get_tenant_quota(request.tenant_id, SEAT_QUOTA).await?
+            .ok_or_else(|| LicenseError::QuotaNotFound { quota_name: SEAT_QUOTA.to_string() })?;
+
+        // Seat assignments are the source of truth for the quota's usage
+        let usage_corrected = quota.current_usage != assigned_seats;
+        if usage_corrected {
+            self.quota_repo.set_quota_usage(request.tenant_id, SEAT_QUOTA, assigned_seats).await?;
+        }
+
+        // A Stripe failure is recorded on the run rather than failing it, so
+        // the quota correction above is still kept
+        let subscription_id = self.license_repo.get_by_tenant_id(request.tenant_id).await?
+            .and_then(|license| license.stripe_subscription_id);
+        let mut billed_seats = None;
+        let mut billing_corrected = false;
+        let mut error = None;
+        if let Some(subscription_id) = subscription_id {
+            match self.billing_service.get_subscription_seats(&subscription_id).await {
+                Ok(seats) => {
+                    billed_seats = Some(seats.quantity);
+                    if request.adjust_billing && seats.quantity != assigned_seats {
+                        match self.billing_service.update_subscription_seats(&seats.item_id, assigned_seats).await {
+                            Ok(()) => billing_corrected = true,
+                            Err(e) => error = Some(e.to_string()),
+                        }
+                    }
+                }
+                Err(e) => error = Some(e.to_string()),
+            }
+        }
+
+        let reconciliation = SeatReconciliation {
+            id: Uuid::new_v4(),
+            tenant_id: request.tenant_id,
+            assigned_seats,
+            recorded_usage: quota.current_usage,
+            billed_seats,
+            usage_corrected,
+            billing_corrected,
+            error,
+            reconciled_at: Utc::now(),
+        };
+        self.seat_repo.record_reconciliation(&reconciliation).await?;
+
+        // Log compliance event
+        if reconciliation.drifted() || reconciliation.error.is_some() {
+            let resolved = reconciliation.error.is_none()
+                && (!request.adjust_billing || billed_seats.map_or(true, |billed| billed == assigned_seats || billing_corrected));
+            let compliance_log = ComplianceLog {
+                id: Uuid::new_v4(),
+                tenant_id: request.tenant_id,
+                event_type: "seat_drift_detected".to_string(),
+                event_category: "license".to_string(),
+                severity: if resolved { "warning".to_string() } else { "error".to_string() },
+                description: format!(
+                    "Seat count drifted: {} assigned, {} recorded, {} billed",
+                    assigned_seats,
+                    reconciliation.recorded_usage,
+                    billed_seats.map_or("none".to_string(), |billed| billed.to_string())
+                ),
+                details: Some(serde_json::to_value(&reconciliation)?),
+                user_id: None,
+                resource_id: Some(reconciliation.id),
+                ip_address: None,
+                resolved,
+                resolved_at: if resolved { Some(Utc::now()) } else { None },
+                resolved_by: None,
+                resolution_notes: if resolved { Some("Corrected by seat reconciliation".to_string()) } else { None },
+                created_at: Utc::now(),
+            };
+            self.compliance_repo.log_compliance_event(compliance_log).await?;
+        }
+
+        Ok(reconciliation)
+    }
+
+    // Dunning activities
+    pub async fn open_dunning_case(&self, invoice: FailedInvoice) -> Result<DunningCase> {
+        let license = self.license_repo.get_by_stripe_customer_id(&invoice.stripe_customer_id).await?
+            .ok_or_else(|| LicenseError::LicenseNotFound(format!("Stripe customer {}", invoice.stripe_customer_id)))?;
+
+        let (case, created) = self.dunning_repo.open(OpenDunningCaseRequest {
+            tenant_id: license.tenant_id,
+            license_id: license.id,
+            stripe_invoice_id: invoice.stripe_invoice_id,
+            amount_due: invoice.amount_due,
+            currency: invoice.currency,
+            next_attempt_at: Utc::now() + Duration::days(self.dunning_config.retry_interval_days),
+        }).await?;
+        if !created {
+            return Ok(case);
+        }
+
+        tracing::info!("Opened dunning case {} for tenant {}", case.id, case.tenant_id);
+        self.log_dunning_event(&case, "payment_failed", "warning", format!(
+            "Payment of {} {} failed for invoice {}", case.amount_due, case.currency, case.stripe_invoice_id
+        )).await?;
+        self.notify_tenant_admin(&case, "opened", SystemEmail::PaymentFailed, |email| {
+            email.variable("next_attempt_date", next_attempt_date(&case))
+        }).await;
+
+        Ok(case)
+    }
+
+    pub async fn get_dunning_case(&self, request: DunningCaseRequest) -> Result<DunningCase> {
+        self.dunning_repo.get(request.case_id).await?
+            .ok_or_else(|| LicenseError::DunningCaseNotFound(request.case_id.to_string()))
+    }
+
+    pub async fn list_due_dunning_cases(&self) -> Result<Vec<DunningCase>> {
+        self.dunning_repo.get_due(100).await
+    }
+
+    /// Retry the invoice's charge. A failure is counted on the case and the
+    /// next retry scheduled, unless this was the last one.
+    pub async fn retry_dunning_charge(&self, request: DunningCaseRequest) -> Result<DunningChargeResult> {
+        let case = self.get_dunning_case(request).await?;
+
+        match self.billing_service.pay_invoice(&case.stripe_invoice_id).await {
+            Ok(()) => Ok(DunningChargeResult { case, paid: true }),
+            Err(LicenseError::PaymentError(error)) => {
+                let next_attempt_at = if case.attempt_count + 1 < self.max_dunning_retries() {
+                    Some(Utc::now() + Duration::days(self.dunning_config.retry_interval_days))
+                } else {
+                    None
+                };
+                let case = self.dunning_repo.record_failed_attempt(case.id, &error, next_attempt_at).await?;
+                tracing::info!("Dunning retry {} of case {} failed: {}", case.attempt_count, case.id, error);
+                Ok(DunningChargeResult { case, paid: false })
+            }
+            Err(e) => Err(e),
+        }
+    }
+
+    /// Switch off the share of the restricted features the case's failed
+    /// retries have earned
+    pub async fn restrict_dunning_features(&self, request: DunningCaseRequest) -> Result<DunningCase> {
+        let mut case = self.get_dunning_case(request).await?;
+        let features = restricted_features_for_attempt(
+            &self.dunning_config.restricted_features,
+            case.attempt_count,
+            self.max_dunning_retries(),
+        );
+
+        let reason = format!("Payment overdue (dunning case {})", case.id);
+        for feature in features.iter().filter(|feature| !case.restricted_features.contains(feature)) {
+            self.tenant_client.restrict_feature(case.tenant_id, feature, &reason).await?;
+        }
+        if features != case.restricted_features {
+            self.dunning_repo.set_restricted_features(case.id, &features).await?;
+            case.restricted_features = features;
+        }
+
+        let stage = format!("attempt-{}", case.attempt_count);
+        self.notify_tenant_admin(&case, &stage, SystemEmail::PaymentOutstanding, |email| {
+            email
+                .variable("attempt_count", case.attempt_count)
+                .variable("restricted_features", if case.restricted_features.is_empty() {
+                    "none".to_string()
+                } else {
+                    case.restricted_features.join(", ")
+                })
+                .variable("next_attempt_date", next_attempt_date(&case))
+        }).await;
+
+        Ok(case)
+    }
+
+    /// Suspend the license once its last retry failed. With a grace period
+    /// the tenant keeps read-only access until it ends, which the gateway
+    /// enforces from the tenant's entitlements; without one the tenant is
+    /// suspended straight away.
+    pub async fn suspend_dunning_tenant(&self, request: DunningCaseRequest) -> Result<DunningCase> {
+        let case = self.get_dunning_case(request).await?;
+
+        if !self.grace_config.enabled {
+            self.tenant_client.suspend(case.tenant_id).await?;
+        }
+        self.license_repo.update(case.license_id, UpdateLicenseRequest {
+            subscription_tier: None,
+            billing_cycle: None,
+            status: Some(LicenseStatus::Suspended),
+            base_price: None,
+            expires_at: None,
+            auto_renew: None,
+            features: None,
+            custom_quotas: None,
+        }).await?;
+        let case = self.dunning_repo.mark_suspended(case.id).await?;
+
+        tracing::warn!("Suspended tenant {} for dunning case {}", case.tenant_id, case.id);
+        self.log_dunning_event(&case, "tenant_suspended_for_nonpayment", "error", format!(
+            "Tenant suspended after {} failed payment retries for invoice {}", case.attempt_count, case.stripe_invoice_id
+        )).await?;
+        if self.grace_config.enabled {
+            let grace_ends_at = Utc::now() + Duration::days(self.grace_config.period_days);
+            self.notify_tenant_admin(&case, "suspended", SystemEmail::AccountReadOnly, |email| {
+                email.variable("grace_ends_date", grace_ends_at.format("%Y-%m-%d").to_string())
+            }).await;
+        } else {
+            self.notify_tenant_admin(&case, "suspended", SystemEmail::AccountSuspended, |email| email).await;
+        }
+
+        Ok(case)
+    }
+
+    /// Close a case as recovered or forgiven: lift its restrictions and
+    /// reactivate a suspended tenant. Forgiving also writes the invoice off
+    /// in Stripe so it stops collecting it.
+    pub async fn resolve_dunning_case(&self, request: ResolveDunningCaseRequest) -> Result<DunningCase> {
+        let case = self.get_dunning_case(DunningCaseRequest { case_id: request.case_id }).await?;
+        if !case.is_open() && case.status != DunningStatus::Suspended {
+            return Ok(case);
+        }
+
+        if request.status == DunningStatus::Forgiven {
+            self.billing_service.mark_invoice_uncollectible(&case.stripe_invoice_id).await?;
+        }
+        for feature in &case.restricted_features {
+            self.tenant_client.lift_feature_restriction(case.tenant_id, feature).await?;
+        }
+        if case.status == DunningStatus::Suspended {
+            self.tenant_client.reactivate(case.tenant_id).await?;
+            self.license_repo.update(case.license_id, UpdateLicenseRequest {
+                subscription_tier: None,
+                billing_cycle: None,
+                status: Some(LicenseStatus::Active),
+                base_price: None,
+                expires_at: None,
+                auto_renew: None,
+                features: None,
+                custom_quotas: None,
+            }).await?;
+        }
+
+        let resolved = self.dunning_repo.resolve(case.id, request.status.clone(), request.resolved_by, request.notes).await?;
+        let event_type = match request.status {
+            DunningStatus::Forgiven => "payment_forgiven",
+            _ => "payment_recovered",
+        };
+        self.log_dunning_event(&resolved, event_type, "info", format!(
+            "Dunning case for invoice {} closed as {:?} after {} retries",
+            resolved.stripe_invoice_id, resolved.status, resolved.attempt_count
+        )).await?;
+        self.notify_tenant_admin(&resolved, "resolved", SystemEmail::PaymentSettled, |email| {
+            email.variable("forgiven", resolved.status == DunningStatus::Forgiven)
+        }).await;
+
+        Ok(resolved)
+    }
+
+    // Billing webhook activities
+    /// Apply a recorded provider event to licenses, billing history and
+    /// dunning. Events we do not act on, or about subscriptions we do not
+    /// know, come back as `Ignored`.
+    pub async fn apply_billing_event(&self, request: WebhookEventRequest) -> Result<WebhookEventStatus> {
+        let event = self.webhook_repo.get(request.webhook_event_id).await?
+            .ok_or_else(|| LicenseError::WebhookEventNotFound(request.webhook_event_id.to_string()))?;
+
+        match event.provider.as_str() {
+            "stripe" => self.apply_stripe_event(&event.event_type, &event.payload["data"]["object"]).await,
+            "paypal" => self.apply_paypal_event(&event.event_type, &event.payload["resource"]).await,
+            provider => Err(LicenseError::ValidationError(format!("Unknown webhook provider: {}", provider))),
+        }
+    }
+
+    pub async fn mark_webhook_event(&self, request: MarkWebhookEventRequest) -> Result<()> {
+        self.webhook_repo.mark(request.webhook_event_id, request.status, request.error).await
+    }
+
+    async fn apply_stripe_event(&self, event_type: &str, object: &serde_json::Value) -> Result<WebhookEventStatus> {
+        let object_id = object["id"].as_str().unwrap_or_default();
+
+        match event_type {
+            "invoice.payment_failed" => {
+                let Some(customer_id) = object["customer"].as_str() else {
+                    return Ok(WebhookEventStatus::Ignored);
+                };
+                let Some(license) = self.license_repo.get_by_stripe_customer_id(customer_id).await? else {
+                    return Ok(WebhookEventStatus::Ignored);
+                };
+                let currency = object["currency"].as_str().unwrap_or("usd").to_uppercase();
+                let amount = stripe_amount(&object["amount_due"], &currency);
+
+                self.record_provider_payment(&license, object_id, amount, &currency, PaymentStatus::Failed, "stripe", stripe_period(object)).await?;
+                self.open_dunning_case(FailedInvoice {
+                    stripe_invoice_id: object_id.to_string(),
+                    stripe_customer_id: customer_id.to_string(),
+                    amount_due: amount,
+                    currency,
+                }).await?;
+                Ok(WebhookEventStatus::Processed)
+            }
+            "invoice.paid" | "invoice.payment_succeeded" => {
+                let Some(customer_id) = object["customer"].as_str() else {
+                    return Ok(WebhookEventStatus::Ignored);
+                };
+                let Some(license) = self.license_repo.get_by_stripe_customer_id(customer_id).await? else {
+                    return Ok(WebhookEventStatus::Ignored);
+                };
+                let currency = object["currency"].as_str().unwrap_or("usd").to_uppercase();
+                self.record_provider_payment(
+                    &license,
+                    object_id,
+                    stripe_amount(&object["amount_paid"], &currency),
+                    &currency,
+                    PaymentStatus::Completed,
+                    "stripe",
+                    stripe_period(object),
+                ).await?;
+
+                // Both events arrive for one payment; the second finds the case closed
+                if let Some(case) = self.dunning_repo.get_by_invoice(object_id).await? {
+                    if case.is_open() || case.status == DunningStatus::Suspended {
+                        self.resolve_dunning_case(ResolveDunningCaseRequest {
+                            case_id: case.id,
+                            status: DunningStatus::Recovered,
+                            resolved_by: None,
+                            notes: Some("Paid in Stripe".to_string()),
+                        }).await?;
+                    }
+                }
+                Ok(WebhookEventStatus::Processed)
+            }
+            "customer.subscription.updated" | "customer.subscription.deleted" => {
+                let Some(license) = self.license_repo.get_by_stripe_subscription_id(object_id).await? else {
+                    return Ok(WebhookEventStatus::Ignored);
+                };
+
+                // Past-due subscriptions are left to dunning
+                let status = match (event_type, object["status"].as_str()) {
+                    ("customer.subscription.deleted", _) | (_, Some("canceled")) => Some(LicenseStatus::Cancelled),
+                    (_, Some("unpaid")) => Some(LicenseStatus::Suspended),
+                    (_, Some("active")) | (_, Some("trialing")) => Some(LicenseStatus::Active),
+                    _ => None,
+                };
+                let auto_renew = if event_type == "customer.subscription.deleted" {
+                    Some(false)
+                } else {
+                    object["cancel_at_period_end"].as_bool().map(|cancelling| !cancelling)
+                };
+                let expires_at = object["current_period_end"].as_i64().and_then(|secs| Utc.timestamp_opt(secs, 0).single());
+
+                self.update_license_state(&license, status, auto_renew, expires_at).await?;
+                Ok(WebhookEventStatus::Processed)
+            }
+            "charge.refunded" => {
+                // Invoice charges are recorded under the invoice, others under the charge
+                let reference = object["invoice"].as_str().unwrap_or(object_id);
+                let Some(record) = self.billing_repo.get_by_payment_reference(reference).await? else {
+                    return Ok(WebhookEventStatus::Ignored);
+                };
+                self.billing_repo.update_payment_status(record.id, PaymentStatus::Refunded, None).await?;
+                Ok(WebhookEventStatus::Processed)
+            }
+            _ => Ok(WebhookEventStatus::Ignored),
+        }
+    }
+
+    async fn apply_paypal_event(&self, event_type: &str, resource: &serde_json::Value) -> Result<WebhookEventStatus> {
+        let resource_id = resource["id"].as_str().unwrap_or_default();
+
+        match event_type {
+            "BILLING.SUBSCRIPTION.ACTIVATED"
+            | "BILLING.SUBSCRIPTION.CANCELLED"
+            | "BILLING.SUBSCRIPTION.SUSPENDED"
+            | "BILLING.SUBSCRIPTION.EXPIRED" => {
+                let Some(license) = self.license_repo.get_by_paypal_subscription_id(resource_id).await? else {
+                    return Ok(WebhookEventStatus::Ignored);
+                };
+                let (status, auto_renew) = match event_type {
+                    "BILLING.SUBSCRIPTION.ACTIVATED" => (LicenseStatus::Active, None),
+                    "BILLING.SUBSCRIPTION.CANCELLED" => (LicenseStatus::Cancelled, Some(false)),
+                    "BILLING.SUBSCRIPTION.SUSPENDED" => (LicenseStatus::Suspended, None),
+                    _ => (LicenseStatus::Expired, Some(false)),
+                };
+                self.update_license_state(&license, Some(status), auto_renew, None).await?;
+                Ok(WebhookEventStatus::Processed)
+            }
+            "PAYMENT.SALE.COMPLETED" => {
+                let Some(subscription_id) = resource["billing_agreement_id"].as_str() else {
+                    return Ok(WebhookEventStatus::Ignored);
+                };
+                let Some(license) = self.license_repo.get_by_paypal_subscription_id(subscription_id).await? else {
+                    return Ok(WebhookEventStatus::Ignored);
+                };
+                let amount = resource["amount"]["total"].as_str()
+                    .and_then(|total| total.parse::<Decimal>().ok())
+                    .unwrap_or(Decimal::ZERO);
+                let currency = resource["amount"]["currency"].as_str().unwrap_or("USD").to_uppercase();
+
+                self.record_provider_payment(&license, resource_id, amount, &currency, PaymentStatus::Completed, "paypal", None).await?;
+                Ok(WebhookEventStatus::Processed)
+            }
+            "PAYMENT.SALE.REFUNDED" => {
+                let Some(sale_id) = resource["sale_id"].as_str() else {
+                    return Ok(WebhookEventStatus::Ignored);
+                };
+                let Some(record) = self.billing_repo.get_by_payment_reference(sale_id).await? else {
+                    return Ok(WebhookEventStatus::Ignored);
+                };
+                self.billing_repo.update_payment_status(record.id, PaymentStatus::Refunded, None).await?;
+                Ok(WebhookEventStatus::Processed)
+            }
+            "BILLING.SUBSCRIPTION.PAYMENT.FAILED" => {
+                let Some(license) = self.license_repo.get_by_paypal_subscription_id(resource_id).await? else {
+                    return Ok(WebhookEventStatus::Ignored);
+                };
+                let compliance_log = ComplianceLog {
+                    id: Uuid::new_v4(),
+                    tenant_id: license.tenant_id,
+                    event_type: "payment_failed".to_string(),
+                    event_category: "billing".to_string(),
+                    severity: "warning".to_string(),
+                    description: format!("PayPal payment failed for subscription {}", resource_id),
+                    details: Some(resource.clone()),
+                    user_id: None,
+                    resource_id: Some(license.id),
+                    ip_address: None,
+                    resolved: false,
+                    resolved_at: None,
+                    resolved_by: None,
+                    resolution_notes: None,
+                    created_at: Utc::now(),
+                };
+                self.compliance_repo.log_compliance_event(compliance_log).await?;
+                Ok(WebhookEventStatus::Processed)
+            }
+            _ => Ok(WebhookEventStatus::Ignored),
+        }
+    }
+
+    async fn update_license_state(
+        &self,
+        license: &License,
+        status: Option<LicenseStatus>,
+        auto_renew: Option<bool>,
+        expires_at: Option<DateTime<Utc>>,
+    ) -> Result<()> {
+        if status.is_none() && auto_renew.is_none() && expires_at.is_none() {
+            return Ok(());
+        }
+
+        self.license_repo.update(license.id, UpdateLicenseRequest {
+            subscription_tier: None,
+            billing_cycle: None,
+            status,
+            base_price: None,
+            expires_at,
+            auto_renew,
+            features: None,
+            custom_quotas: None,
+        }).await?;
+        Ok(())
+    }
+
+    /// Record a provider payment in billing history under its reference,
+    /// updating the record when the provider already reported on it
+    async fn record_provider_payment(
+        &self,
+        license: &License,
+        reference: &str,
+        amount: Decimal,
+        currency: &str,
+        status: PaymentStatus,
+        payment_method: &str,
+        period: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    ) -> Result<()> {
+        if let Some(record) = self.billing_repo.get_by_payment_reference(reference).await? {
+            return self.billing_repo.update_payment_status(record.id, status, None).await;
+        }
+
+        let now = Utc::now();
+        let (period_start, period_end) = period.unwrap_or((now, license.expires_at.unwrap_or(now)));
+        let record = self.billing_repo.create_billing_record(BillingHistory {
+            id: Uuid::new_v4(),
+            tenant_id: license.tenant_id,
+            license_id: license.id,
+            invoice_number: self.billing_service.generate_invoice_number().await,
+            amount,
+            currency: currency.to_string(),
+            tax_amount: Decimal::ZERO,
+            billing_period_start: period_start,
+            billing_period_end: period_end,
+            payment_status: status.clone(),
+            payment_method: Some(payment_method.to_string()),
+            payment_reference: Some(reference.to_string()),
+            paid_at: None,
+            usage_details: None,
+            created_at: now,
+            updated_at: now,
+        }).await?;
+
+        // The insert leaves paid_at unset
+        if matches!(status, PaymentStatus::Completed) {
+            self.billing_repo.update_payment_status(record.id, status, None).await?;
+        }
+        Ok(())
+    }
+
+    // Pricing activities
+    pub async fn quote_plan_price(&self, request: QuotePlanPriceRequest) -> Result<PlanPrice> {
+        let currency = self.tenant_currency(request.tenant_id).await?;
+        self.plan_price(&request.subscription_tier, &request.billing_cycle, &currency).await
+    }
+
+    /// The currency a tenant is priced and billed in
+    pub async fn tenant_currency(&self, tenant_id: Uuid) -> Result<String> {
+        Ok(match self.pricing_repo.get_tenant_currency(tenant_id).await? {
+            Some(tenant_currency) => tenant_currency.currency,
+            None => self.billing_service.config().default_currency.clone(),
+        })
+    }
+
+    /// A plan's price from the currency's price book, or else the default
+    /// currency's price converted at the latest exchange rate. Plans with
+    /// no price at all (custom plans) are free until priced.
+    pub async fn plan_price(&self, tier: &SubscriptionTier, cycle: &BillingCycle, currency: &str) -> Result<PlanPrice> {
+        let currency = pricing::normalize_currency(currency)?;
+        if let Some(entry) = self.pricing_repo.get_entry(&currency, tier, cycle).await? {
+            return Ok(PlanPrice {
+                subscription_tier: entry.subscription_tier,
+                billing_cycle: entry.billing_cycle,
+                currency: entry.currency,
+                price: entry.price,
+                setup_fee: entry.setup_fee,
+                stripe_price_id: entry.stripe_price_id,
+                exchange_rate: None,
+            });
+        }
+
+        let default_currency = self.billing_service.config().default_currency.to_uppercase();
+        let zero_price = PlanPrice {
+            subscription_tier: tier.clone(),
+            billing_cycle: cycle.clone(),
+            currency: currency.clone(),
+            price: Decimal::ZERO,
+            setup_fee: Decimal::ZERO,
+            stripe_price_id: None,
+            exchange_rate: None,
+        };
+        if currency == default_currency {
+            return Ok(zero_price);
+        }
+        let Some(entry) = self.pricing_repo.get_entry(&default_currency, tier, cycle).await? else {
+            return Ok(zero_price);
+        };
+        let rate = self.pricing_repo.latest_rate(&default_currency, &currency).await?
+            .ok_or_else(|| LicenseError::ValidationError(format!(
+                "No {} price for the {:?} plan and no {}/{} exchange rate to convert one",
+                currency, tier, default_currency, currency
+            )))?;
+
+        Ok(PlanPrice {
+            subscription_tier: entry.subscription_tier,
+            billing_cycle: entry.billing_cycle,
+            currency,
+            price: pricing::convert(entry.price, &rate),
+            setup_fee: pricing::convert(entry.setup_fee, &rate),
+            // Stripe prices are per currency, so a converted price has none
+            stripe_price_id: None,
+            exchange_rate: Some(rate),
+        })
+    }
+
     // Helper methods
-    fn get_tier_price(&self, tier: &SubscriptionTier, cycle: &BillingCycle) -> Decimal {
-        use rust_decimal_macros::dec;
-        
-        match (tier, cycle) {
-            (SubscriptionTier::Free, _) => dec!(0.00),
-            (SubscriptionTier::Professional, BillingCycle::Monthly) => dec!(29.00),
-            (SubscriptionTier::Professional, BillingCycle::Yearly) => dec!(290.00),
-            (SubscriptionTier::Enterprise, BillingCycle::Monthly) => dec!(99.00),
-            (SubscriptionTier::Enterprise, BillingCycle::Yearly) => dec!(990.00),
-            (SubscriptionTier::Custom, _) => dec!(0.00), // Custom pricing
-            _ => dec!(0.00),
+    fn stripe_price_id(&self, price: &PlanPrice) -> String {
+        match &price.stripe_price_id {
+            Some(price_id) => price_id.clone(),
+            None => {
+                tracing::warn!(
+                    "No {} Stripe price for the {:?} {:?} plan, using the default price",
+                    price.currency, price.subscription_tier, price.billing_cycle
+                );
+                self.get_price_id(&price.subscription_tier, &price.billing_cycle)
+            }
+        }
+    }
+
+    fn max_dunning_retries(&self) -> i32 {
+        let billing = self.billing_service.config();
+        if billing.retry_failed_payments { billing.max_payment_retries } else { 0 }
+    }
+
+    async fn log_dunning_event(&self, case: &DunningCase, event_type: &str, severity: &str, description: String) -> Result<()> {
+        let compliance_log = ComplianceLog {
+            id: Uuid::new_v4(),
+            tenant_id: case.tenant_id,
+            event_type: event_type.to_string(),
+            event_category: "billing".to_string(),
+            severity: severity.to_string(),
+            description,
+            details: Some(serde_json::to_value(case)?),
+            user_id: case.resolved_by.or(case.paused_by),
+            resource_id: Some(case.id),
+            ip_address: None,
+            resolved: !case.is_open(),
+            resolved_at: case.resolved_at,
+            resolved_by: case.resolved_by,
+            resolution_notes: case.resolution_notes.clone(),
+            created_at: Utc::now(),
+        };
+        self.compliance_repo.log_compliance_event(compliance_log).await?;
+        Ok(())
+    }
+
+    async fn redeemable_coupon(&self, tenant_id: Uuid, code: &str, tier: &SubscriptionTier, currency: &str) -> Result<Coupon> {
+        let code = promotions::normalize_code(code);
+        let coupon = self.promotion_repo.get_coupon_by_code(&code).await?
+            .ok_or_else(|| LicenseError::CouponNotFound(code.clone()))?;
+        let tenant_redemptions = self.promotion_repo.count_tenant_redemptions(coupon.id, tenant_id).await?;
+
+        promotions::check_redeemable(&coupon, tier, currency, tenant_redemptions, Utc::now())?;
+        Ok(coupon)
+    }
+
+    async fn log_promotion_event(
+        &self,
+        tenant_id: Uuid,
+        event_type: &str,
+        severity: &str,
+        description: String,
+        details: serde_json::Value,
+        resource_id: Uuid,
+    ) -> Result<()> {
+        let compliance_log = ComplianceLog {
+            id: Uuid::new_v4(),
+            tenant_id,
+            event_type: event_type.to_string(),
+            event_category: "billing".to_string(),
+            severity: severity.to_string(),
+            description,
+            details: Some(details),
+            user_id: None,
+            resource_id: Some(resource_id),
+            ip_address: None,
+            resolved: true,
+            resolved_at: Some(Utc::now()),
+            resolved_by: None,
+            resolution_notes: None,
+            created_at: Utc::now(),
+        };
+        self.compliance_repo.log_compliance_event(compliance_log).await?;
+        Ok(())
+    }
+
+    /// Send the tenant admin a branded dunning email about `case`, once per
+    /// case and stage. Emails are best effort: a failed notification does
+    /// not hold up dunning.
+    async fn notify_tenant_admin(
+        &self,
+        case: &DunningCase,
+        stage: &str,
+        template: SystemEmail,
+        variables: impl FnOnce(SendEmailRequest) -> SendEmailRequest,
+    ) {
+        let result = match self.tenant_client.get_admin_email(case.tenant_id).await {
+            Ok(email) => {
+                let request = SendEmailRequest::new(Some(case.tenant_id.to_string()), template, EmailRecipient::new(email))
+                    .variable("amount", case.amount_due.to_string())
+                    .variable("currency", case.currency.clone())
+                    .tag("billing")
+                    .tag("dunning")
+                    .idempotency_key(format!("dunning:{}:{}", case.id, stage));
+                self.email_client.send(variables(request)).await
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = result {
+            tracing::warn!("Failed to email the admin of tenant {}: {}", case.tenant_id, e);
         }
     }
 
@@ -530,4 +1653,33 @@ impl LicenseActivities {
             _ => "price_default".to_string(),
         }
     }
-}
\ No newline at end of file
+}
+
+/// The leading share of `features` restricted after `attempt` failed
+/// retries out of `max_retries`, so the last retry restricts all of them
+fn restricted_features_for_attempt(features: &[String], attempt: i32, max_retries: i32) -> Vec<String> {
+    if max_retries <= 0 || attempt <= 0 {
+        return Vec::new();
+    }
+    let attempt = attempt.min(max_retries) as usize;
+    let count = (features.len() * attempt).div_ceil(max_retries as usize);
+    features[..count].to_vec()
+}
+
+/// When the case's charge is retried next, for dunning emails
+fn next_attempt_date(case: &DunningCase) -> String {
+    case.next_attempt_at
+        .map_or("the next retry".to_string(), |at| at.format("%Y-%m-%d").to_string())
+}
+
+/// Stripe amounts are in the currency's minor unit
+fn stripe_amount(value: &serde_json::Value, currency: &str) -> Decimal {
+    pricing::from_minor_units(value.as_i64().unwrap_or(0), currency)
+}
+
+/// The billing period of a Stripe invoice
+fn stripe_period(invoice: &serde_json::Value) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+    let start = Utc.timestamp_opt(invoice["period_start"].as_i64()?, 0).single()?;
+    let end = Utc.timestamp_opt(invoice["period_end"].as_i64()?, 0).single()?;
+    Some((start, end))
+}
@@ -1,6 +1,8 @@
 use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
+use sha2::Sha256;
 // Stripe integration using direct HTTP API calls
 use uuid::Uuid;
 
@@ -8,8 +10,66 @@ use crate::{
     config::{StripeConfig, PayPalConfig, BillingConfig},
     error::{LicenseError, Result},
     models::*,
+    pricing,
 };
 
+/// How far a webhook delivery's timestamp may be from now before it is
+/// rejected as a replay
+const WEBHOOK_TOLERANCE_SECONDS: i64 = 300;
+
+/// Check a `Stripe-Signature` header. It holds the timestamp and one or
+/// more HMAC-SHA256 signatures of "{timestamp}.{payload}".
+fn verify_stripe_signature(secret: &str, payload: &str, signature_header: &str, now: i64) -> Result<()> {
+    let mut timestamp = None;
+    let mut signatures = Vec::new();
+    for part in signature_header.split(',') {
+        match part.trim().split_once('=') {
+            Some(("t", value)) => timestamp = value.parse::<i64>().ok(),
+            Some(("v1", value)) => signatures.push(value),
+            _ => {}
+        }
+    }
+    let timestamp = timestamp
+        .ok_or_else(|| LicenseError::ValidationError("Stripe signature has no timestamp".to_string()))?;
+    if (now - timestamp).abs() > WEBHOOK_TOLERANCE_SECONDS {
+        return Err(LicenseError::ValidationError("Stripe webhook timestamp is outside the tolerance".to_string()));
+    }
+
+    let verified = signatures.iter().any(|signature| {
+        let Ok(signature) = hex::decode(signature) else {
+            return false;
+        };
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+            .expect("HMAC accepts keys of any length");
+        mac.update(timestamp.to_string().as_bytes());
+        mac.update(b".");
+        mac.update(payload.as_bytes());
+        mac.verify_slice(&signature).is_ok()
+    });
+    if !verified {
+        return Err(LicenseError::ValidationError("Stripe webhook signature does not match".to_string()));
+    }
+    Ok(())
+}
+
+/// PayPal signs the transmission time, so an old one is a replayed delivery
+fn check_paypal_transmission_time(transmission_time: &str, now: DateTime<Utc>) -> Result<()> {
+    let sent_at = DateTime::parse_from_rfc3339(transmission_time)
+        .map_err(|_| LicenseError::ValidationError(format!("PayPal transmission time {} is not a timestamp", transmission_time)))?;
+    if (now.timestamp() - sent_at.timestamp()).abs() > WEBHOOK_TOLERANCE_SECONDS {
+        return Err(LicenseError::ValidationError("PayPal webhook transmission time is outside the tolerance".to_string()));
+    }
+    Ok(())
+}
+
+/// PayPal's answer to a verify-webhook-signature call
+fn check_paypal_verification(verification: &serde_json::Value) -> Result<()> {
+    if verification["verification_status"].as_str() != Some("SUCCESS") {
+        return Err(LicenseError::ValidationError("PayPal webhook signature does not match".to_string()));
+    }
+    Ok(())
+}
+
 #[derive(Debug, Clone)]
 pub struct BillingService {
     stripe_client: Option<StripeHttpClient>,
@@ -67,6 +127,66 @@ impl BillingService {
         }
     }
 
+    pub async fn get_subscription_seats(&self, subscription_id: &str) -> Result<SubscriptionSeats> {
+        if let Some(ref client) = self.stripe_client {
+            client.get_subscription_seats(subscription_id).await
+        } else {
+            Err(LicenseError::ConfigError("Stripe not configured".to_string()))
+        }
+    }
+
+    pub async fn update_subscription_seats(&self, item_id: &str, quantity: i64) -> Result<()> {
+        if let Some(ref client) = self.stripe_client {
+            client.update_subscription_seats(item_id, quantity).await
+        } else {
+            Err(LicenseError::ConfigError("Stripe not configured".to_string()))
+        }
+    }
+
+    pub async fn update_subscription_price(&self, subscription_id: &str, price_id: &str) -> Result<()> {
+        if let Some(ref client) = self.stripe_client {
+            client.update_subscription_price(subscription_id, price_id).await
+        } else {
+            Err(LicenseError::ConfigError("Stripe not configured".to_string()))
+        }
+    }
+
+    pub async fn pay_invoice(&self, invoice_id: &str) -> Result<()> {
+        if let Some(ref client) = self.stripe_client {
+            client.pay_invoice(invoice_id).await
+        } else {
+            Err(LicenseError::ConfigError("Stripe not configured".to_string()))
+        }
+    }
+
+    pub async fn mark_invoice_uncollectible(&self, invoice_id: &str) -> Result<()> {
+        if let Some(ref client) = self.stripe_client {
+            client.mark_invoice_uncollectible(invoice_id).await
+        } else {
+            Err(LicenseError::ConfigError("Stripe not configured".to_string()))
+        }
+    }
+
+    pub fn verify_webhook(&self, payload: &str, signature_header: &str) -> Result<StripeEvent> {
+        if let Some(ref client) = self.stripe_client {
+            client.verify_webhook(payload, signature_header)
+        } else {
+            Err(LicenseError::ConfigError("Stripe not configured".to_string()))
+        }
+    }
+
+    pub async fn verify_paypal_webhook(&self, headers: &PayPalWebhookHeaders, payload: &str) -> Result<PayPalEvent> {
+        if let Some(ref client) = self.paypal_client {
+            client.verify_webhook(headers, payload).await
+        } else {
+            Err(LicenseError::ConfigError("PayPal not configured".to_string()))
+        }
+    }
+
+    pub fn config(&self) -> &BillingConfig {
+        &self.config
+    }
+
     pub async fn create_invoice(&self, invoice: &BillingInvoice) -> Result<String> {
         if let Some(ref client) = self.stripe_client {
             client.create_invoice(invoice).await
@@ -112,6 +232,8 @@ impl BillingService {
                 unit_price,
                 total_price: line_total,
                 item_type: "usage".to_string(),
+                tax_amount: Decimal::ZERO,
+                tax_breakdown: Vec::new(),
             });
             
             total_amount += line_total;
@@ -126,6 +248,8 @@ impl BillingService {
                 unit_price: tax_amount,
                 total_price: tax_amount,
                 item_type: "tax".to_string(),
+                tax_amount: Decimal::ZERO,
+                tax_breakdown: Vec::new(),
             });
         }
 
@@ -149,9 +273,56 @@ impl BillingService {
             billing_period_end,
             line_items,
             usage_summary: Some(serde_json::to_value(&usage_by_type)?),
+            reverse_charge: false,
+            tax_note: None,
+            exchange_rate: None,
         })
     }
 
+    /// Price an hour of a marketplace module's usage and split each charge
+    /// between the module's developer and the platform
+    pub fn module_usage_charges(&self, request: &RecordModuleUsageRequest) -> Result<Vec<ModuleUsageCharge>> {
+        if request.period_end <= request.period_start {
+            return Err(LicenseError::ValidationError("Usage period must end after it starts".to_string()));
+        }
+        let developer_share = Decimal::try_from(self.config.developer_revenue_share)
+            .map_err(|e| LicenseError::ConfigError(format!("Invalid developer revenue share: {}", e)))?;
+
+        let mut charges = Vec::new();
+        for line in &request.lines {
+            let quantity = Decimal::try_from(line.quantity)
+                .map_err(|_| LicenseError::ValidationError(format!("Invalid quantity for {}", line.metric)))?;
+            let unit_price = Decimal::try_from(line.unit_price)
+                .map_err(|_| LicenseError::ValidationError(format!("Invalid unit price for {}", line.metric)))?;
+            if quantity.is_sign_negative() || unit_price.is_sign_negative() {
+                return Err(LicenseError::ValidationError(format!("Negative usage charge for {}", line.metric)));
+            }
+
+            let amount = (quantity * unit_price).round_dp(8);
+            let developer_amount = (amount * developer_share).round_dp(8);
+            charges.push(ModuleUsageCharge {
+                id: Uuid::new_v4(),
+                tenant_id: request.tenant_id,
+                module_id: request.module_id.clone(),
+                instance_id: request.instance_id,
+                developer_id: request.developer_id.clone(),
+                period_start: request.period_start,
+                period_end: request.period_end,
+                metric: line.metric.clone(),
+                quantity,
+                unit_price,
+                amount,
+                developer_amount,
+                platform_amount: amount - developer_amount,
+                currency: request.currency.clone(),
+                invoice_number: None,
+                created_at: Utc::now(),
+            });
+        }
+
+        Ok(charges)
+    }
+
     fn get_usage_pricing(&self, operation_type: &str) -> (Decimal, String) {
         match operation_type {
             "api_call" => (Decimal::from_str("0.001").unwrap(), "API Calls".to_string()),
@@ -218,6 +389,48 @@ impl PayPalClient {
         }
     }
 
+    /// Have PayPal check a webhook delivery's signature against the
+    /// configured webhook, then parse its event
+    pub async fn verify_webhook(&self, headers: &PayPalWebhookHeaders, payload: &str) -> Result<PayPalEvent> {
+        let base_url = if self.config.environment == "sandbox" {
+            "https://api.sandbox.paypal.com"
+        } else {
+            "https://api.paypal.com"
+        };
+
+        check_paypal_transmission_time(&headers.transmission_time, Utc::now())?;
+        let webhook_event: serde_json::Value = serde_json::from_str(payload)?;
+        let access_token = self.get_access_token().await?;
+
+        let verification_request = serde_json::json!({
+            "auth_algo": headers.auth_algo,
+            "cert_url": headers.cert_url,
+            "transmission_id": headers.transmission_id,
+            "transmission_sig": headers.transmission_sig,
+            "transmission_time": headers.transmission_time,
+            "webhook_id": self.config.webhook_id,
+            "webhook_event": webhook_event
+        });
+
+        let response = self.client
+            .post(&format!("{}/v1/notifications/verify-webhook-signature", base_url))
+            .header("Authorization", format!("Bearer {}", access_token))
+            .header("Content-Type", "application/json")
+            .json(&verification_request)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(LicenseError::PaymentError(format!("PayPal webhook verification failed: {}", error_text)));
+        }
+
+        let verification: serde_json::Value = response.json().await?;
+        check_paypal_verification(&verification)?;
+
+        Ok(serde_json::from_value(webhook_event)?)
+    }
+
     async fn get_access_token(&self) -> Result<String> {
         let base_url = if self.config.environment == "sandbox" {
             "https://api.sandbox.paypal.com"
@@ -245,6 +458,39 @@ impl PayPalClient {
     }
 }
 
+/// A Stripe webhook event; `data.object` is the invoice, subscription or
+/// other object the event is about
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StripeEvent {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub event_type: String,
+    pub data: StripeEventData,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct StripeEventData {
+    pub object: serde_json::Value,
+}
+
+/// A PayPal webhook event; `resource` is the subscription or sale it is about
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PayPalEvent {
+    pub id: String,
+    pub event_type: String,
+    pub resource: serde_json::Value,
+}
+
+/// The PAYPAL-* transmission headers PayPal signs its deliveries with
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct PayPalWebhookHeaders {
+    pub transmission_id: String,
+    pub transmission_time: String,
+    pub transmission_sig: String,
+    pub cert_url: String,
+    pub auth_algo: String,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct PaymentResult {
     pub payment_id: String,
@@ -254,6 +500,13 @@ pub struct PaymentResult {
     pub client_secret: Option<String>,
 }
 
+/// The seat line of a Stripe subscription, the first item on it
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubscriptionSeats {
+    pub item_id: String,
+    pub quantity: i64,
+}
+
 use base64;
 use rust_decimal_macros::dec;
 
@@ -333,6 +586,125 @@ impl StripeHttpClient {
         }
     }
 
+    pub async fn get_subscription_seats(&self, subscription_id: &str) -> Result<SubscriptionSeats> {
+        let response = self.client
+            .get(&format!("https://api.stripe.com/v1/subscriptions/{}", subscription_id))
+            .header("Authorization", format!("Bearer {}", self.config.secret_key))
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            let subscription: serde_json::Value = response.json().await?;
+            let item = &subscription["items"]["data"][0];
+            let item_id = item["id"].as_str()
+                .ok_or_else(|| LicenseError::SubscriptionNotFound(format!("Subscription {} has no items", subscription_id)))?;
+
+            Ok(SubscriptionSeats {
+                item_id: item_id.to_string(),
+                quantity: item["quantity"].as_i64().unwrap_or(0),
+            })
+        } else {
+            let error_text = response.text().await?;
+            Err(LicenseError::PaymentError(format!("Stripe subscription lookup failed: {}", error_text)))
+        }
+    }
+
+    pub async fn update_subscription_seats(&self, item_id: &str, quantity: i64) -> Result<()> {
+        let quantity = quantity.to_string();
+        let params = [
+            ("quantity", quantity.as_str()),
+            ("proration_behavior", "create_prorations"),
+        ];
+
+        let response = self.client
+            .post(&format!("https://api.stripe.com/v1/subscription_items/{}", item_id))
+            .header("Authorization", format!("Bearer {}", self.config.secret_key))
+            .form(&params)
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            let error_text = response.text().await?;
+            Err(LicenseError::PaymentError(format!("Stripe subscription quantity update failed: {}", error_text)))
+        }
+    }
+
+    /// Move the subscription to another price. Proration is charged by the
+    /// plan change itself, so Stripe is told not to prorate again.
+    pub async fn update_subscription_price(&self, subscription_id: &str, price_id: &str) -> Result<()> {
+        let seats = self.get_subscription_seats(subscription_id).await?;
+        let params = [
+            ("items[0][id]", seats.item_id.as_str()),
+            ("items[0][price]", price_id),
+            ("proration_behavior", "none"),
+        ];
+
+        let response = self.client
+            .post(&format!("https://api.stripe.com/v1/subscriptions/{}", subscription_id))
+            .header("Authorization", format!("Bearer {}", self.config.secret_key))
+            .form(&params)
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            let error_text = response.text().await?;
+            Err(LicenseError::PaymentError(format!("Stripe subscription price update failed: {}", error_text)))
+        }
+    }
+
+    /// Retry the charge of an open invoice. A declined card comes back as
+    /// a `PaymentError` with Stripe's message.
+    pub async fn pay_invoice(&self, invoice_id: &str) -> Result<()> {
+        let response = self.client
+            .post(&format!("https://api.stripe.com/v1/invoices/{}/pay", invoice_id))
+            .header("Authorization", format!("Bearer {}", self.config.secret_key))
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            let invoice: serde_json::Value = response.json().await?;
+            match invoice["status"].as_str() {
+                Some("paid") => Ok(()),
+                status => Err(LicenseError::PaymentError(format!(
+                    "Stripe invoice {} is {} after the retry",
+                    invoice_id,
+                    status.unwrap_or("unknown")
+                ))),
+            }
+        } else {
+            let error: serde_json::Value = response.json().await.unwrap_or_default();
+            let message = error["error"]["message"].as_str().unwrap_or("unknown error");
+            Err(LicenseError::PaymentError(format!("Stripe invoice payment failed: {}", message)))
+        }
+    }
+
+    /// Write an invoice off so Stripe stops collecting it
+    pub async fn mark_invoice_uncollectible(&self, invoice_id: &str) -> Result<()> {
+        let response = self.client
+            .post(&format!("https://api.stripe.com/v1/invoices/{}/mark_uncollectible", invoice_id))
+            .header("Authorization", format!("Bearer {}", self.config.secret_key))
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            let error_text = response.text().await?;
+            Err(LicenseError::PaymentError(format!("Stripe invoice write-off failed: {}", error_text)))
+        }
+    }
+
+    /// Check the `Stripe-Signature` header of a webhook delivery and parse
+    /// its event
+    pub fn verify_webhook(&self, payload: &str, signature_header: &str) -> Result<StripeEvent> {
+        verify_stripe_signature(&self.config.webhook_secret, payload, signature_header, Utc::now().timestamp())?;
+        Ok(serde_json::from_str(payload)?)
+    }
+
     pub async fn create_invoice(&self, invoice: &BillingInvoice) -> Result<String> {
         let params = [
             ("customer", invoice.tenant_id.to_string().as_str()), // This should be customer_id
@@ -363,10 +735,10 @@ impl StripeHttpClient {
     }
 
     pub async fn process_payment(&self, amount: Decimal, currency: &str, customer_id: &str) -> Result<PaymentResult> {
-        let amount_cents = (amount * Decimal::from(100)).to_i64().unwrap_or(0);
+        let amount_minor = pricing::to_minor_units(amount, currency);
         
         let params = [
-            ("amount", amount_cents.to_string().as_str()),
+            ("amount", amount_minor.to_string().as_str()),
             ("currency", currency),
             ("customer", customer_id),
             ("automatic_payment_methods[enabled]", "true"),
@@ -419,4 +791,72 @@ impl DecimalFromStr for Decimal {
     fn from_str(s: &str) -> Result<Decimal, rust_decimal::Error> {
         s.parse()
     }
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    const SECRET: &str = "whsec_test";
+    const PAYLOAD: &str = r#"{"id":"evt_1","type":"invoice.paid","data":{"object":{}}}"#;
+
+    fn stripe_signature(secret: &str, timestamp: i64, payload: &str) -> String {
+        let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).unwrap();
+        mac.update(format!("{}.{}", timestamp, payload).as_bytes());
+        hex::encode(mac.finalize().into_bytes())
+    }
+
+    #[test]
+    fn test_stripe_signature_valid() {
+        let header = format!("t=1700000000,v1={}", stripe_signature(SECRET, 1_700_000_000, PAYLOAD));
+        assert!(verify_stripe_signature(SECRET, PAYLOAD, &header, 1_700_000_100).is_ok());
+
+        // Any of several signatures may match, as while the secret is rolled
+        let header = format!(
+            "t=1700000000,v1={},v1={}",
+            stripe_signature("whsec_old", 1_700_000_000, PAYLOAD),
+            stripe_signature(SECRET, 1_700_000_000, PAYLOAD)
+        );
+        assert!(verify_stripe_signature(SECRET, PAYLOAD, &header, 1_700_000_000).is_ok());
+    }
+
+    #[test]
+    fn test_stripe_signature_tampered() {
+        let header = format!("t=1700000000,v1={}", stripe_signature(SECRET, 1_700_000_000, PAYLOAD));
+        let tampered = PAYLOAD.replace("invoice.paid", "invoice.payment_failed");
+        assert!(verify_stripe_signature(SECRET, &tampered, &header, 1_700_000_000).is_err());
+        assert!(verify_stripe_signature("whsec_other", PAYLOAD, &header, 1_700_000_000).is_err());
+
+        // The timestamp is signed too
+        let moved = header.replace("t=1700000000", "t=1700000060");
+        assert!(verify_stripe_signature(SECRET, PAYLOAD, &moved, 1_700_000_060).is_err());
+
+        assert!(verify_stripe_signature(SECRET, PAYLOAD, "t=1700000000,v1=not-hex", 1_700_000_000).is_err());
+        assert!(verify_stripe_signature(SECRET, PAYLOAD, "v1=abcd", 1_700_000_000).is_err());
+    }
+
+    #[test]
+    fn test_stripe_signature_expired_timestamp() {
+        let header = format!("t=1700000000,v1={}", stripe_signature(SECRET, 1_700_000_000, PAYLOAD));
+        assert!(verify_stripe_signature(SECRET, PAYLOAD, &header, 1_700_000_300).is_ok());
+        assert!(verify_stripe_signature(SECRET, PAYLOAD, &header, 1_700_000_301).is_err());
+        assert!(verify_stripe_signature(SECRET, PAYLOAD, &header, 1_699_999_600).is_err());
+    }
+
+    #[test]
+    fn test_paypal_transmission_time() {
+        let now = Utc.with_ymd_and_hms(2025, 3, 1, 12, 0, 0).unwrap();
+        assert!(check_paypal_transmission_time("2025-03-01T11:58:00Z", now).is_ok());
+        assert!(check_paypal_transmission_time("2025-03-01T11:50:00Z", now).is_err());
+        assert!(check_paypal_transmission_time("2025-03-01T12:58:00+01:00", now).is_ok());
+        assert!(check_paypal_transmission_time("yesterday", now).is_err());
+    }
+
+    #[test]
+    fn test_paypal_verification_status() {
+        assert!(check_paypal_verification(&serde_json::json!({ "verification_status": "SUCCESS" })).is_ok());
+        assert!(check_paypal_verification(&serde_json::json!({ "verification_status": "FAILURE" })).is_err());
+        assert!(check_paypal_verification(&serde_json::json!({})).is_err());
+    }
+}
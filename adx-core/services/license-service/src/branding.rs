@@ -0,0 +1,91 @@
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    config::DocumentConfig,
+    error::{LicenseError, Result},
+};
+
+/// Colours of documents of tenants without white-label branding
+const DEFAULT_PRIMARY_COLOR: &str = "#1f2937";
+const DEFAULT_ACCENT_COLOR: &str = "#2563eb";
+
+/// The parts of a tenant's white-label branding applied to billing documents
+#[derive(Debug, Clone)]
+pub struct DocumentBranding {
+    pub brand_name: String,
+    /// Hex colours, e.g. "#1f2937"
+    pub primary_color: String,
+    pub accent_color: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct WhiteLabelBranding {
+    brand_name: String,
+    primary_color: String,
+    accent_color: String,
+}
+
+/// Looks up tenant branding in white-label-service
+#[derive(Debug, Clone)]
+pub struct BrandingClient {
+    client: reqwest::Client,
+    base_url: String,
+    default_brand_name: String,
+}
+
+impl BrandingClient {
+    pub fn new(config: &DocumentConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: config.white_label_service_url.trim_end_matches('/').to_string(),
+            default_brand_name: config.default_brand_name.clone(),
+        }
+    }
+
+    /// The tenant's branding, or the platform's when the tenant has none or
+    /// white-label-service can't be reached. Documents are never held back
+    /// for want of branding.
+    pub async fn get(&self, tenant_id: Uuid) -> DocumentBranding {
+        match self.fetch(tenant_id).await {
+            Ok(Some(branding)) => branding,
+            Ok(None) => self.default_branding(),
+            Err(e) => {
+                tracing::warn!("Using default branding for tenant {}: {}", tenant_id, e);
+                self.default_branding()
+            }
+        }
+    }
+
+    async fn fetch(&self, tenant_id: Uuid) -> Result<Option<DocumentBranding>> {
+        let response = self.client
+            .get(&format!("{}/api/v1/white-label/branding", self.base_url))
+            .query(&[("tenant_id", tenant_id.to_string())])
+            .header("X-Tenant-ID", tenant_id.to_string())
+            .send()
+            .await?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(LicenseError::Internal(format!("White-label service branding lookup failed: {}", error_text)));
+        }
+
+        let branding: WhiteLabelBranding = response.json().await?;
+        Ok(Some(DocumentBranding {
+            brand_name: branding.brand_name,
+            primary_color: branding.primary_color,
+            accent_color: branding.accent_color,
+        }))
+    }
+
+    fn default_branding(&self) -> DocumentBranding {
+        DocumentBranding {
+            brand_name: self.default_brand_name.clone(),
+            primary_color: DEFAULT_PRIMARY_COLOR.to_string(),
+            accent_color: DEFAULT_ACCENT_COLOR.to_string(),
+        }
+    }
+}
@@ -5,11 +5,24 @@ pub struct LicenseConfig {
     pub database_url: String,
     pub redis_url: String,
     pub server_port: u16,
+    /// Internal gRPC API for quota checks and seat changes
+    pub grpc_port: u16,
+    /// Tenant-service, told about plan changes so feature flags follow them
+    pub tenant_service_url: String,
+    /// Module-service, asked which marketplace modules a tenant holds
+    pub module_service_url: String,
     pub temporal: TemporalConfig,
     pub stripe: StripeConfig,
     pub paypal: PayPalConfig,
     pub billing: BillingConfig,
     pub quotas: QuotaConfig,
+    pub seats: SeatConfig,
+    pub dunning: DunningConfig,
+    pub grace: GraceConfig,
+    pub notifications: NotificationConfig,
+    pub documents: DocumentConfig,
+    pub tax: TaxConfig,
+    pub currencies: CurrencyConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -45,6 +58,8 @@ pub struct BillingConfig {
     pub grace_period_days: i32,
     pub retry_failed_payments: bool,
     pub max_payment_retries: i32,
+    /// Share of marketplace module usage revenue paid to the module's developer
+    pub developer_revenue_share: f64,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -54,6 +69,94 @@ pub struct QuotaConfig {
     pub usage_aggregation_interval_seconds: u64,
     pub warning_notification_enabled: bool,
     pub auto_suspend_on_violation: bool,
+    /// How long a tenant's quota limit is cached in Redis before it is read
+    /// from the database again
+    pub limit_cache_seconds: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SeatConfig {
+    /// Run the seat reconciliation nightly in worker mode
+    pub reconciliation_enabled: bool,
+    /// Hour of the day, in UTC, the reconciliation runs at
+    pub reconciliation_hour_utc: u32,
+    /// Adjust Stripe subscription quantities to the assigned seats
+    pub adjust_billing: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DunningConfig {
+    /// Work through due dunning cases in worker mode
+    pub enabled: bool,
+    /// Minutes between checks for due charge retries
+    pub check_interval_minutes: u64,
+    /// Days between retries of a failed charge; the number of retries is
+    /// `billing.max_payment_retries`
+    pub retry_interval_days: i64,
+    /// Feature flags switched off as retries fail, in order. Each failed
+    /// retry switches off its share of the list; the tenant is suspended
+    /// once the last retry fails.
+    pub restricted_features: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraceConfig {
+    /// Tenants whose license lapses keep read-only access for a grace
+    /// period instead of being suspended straight away
+    pub enabled: bool,
+    /// Days of read-only access after the license lapses; once they are up
+    /// the tenant is suspended
+    pub period_days: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct NotificationConfig {
+    /// Renders and delivers the tenant's branded system emails
+    pub white_label_service_url: String,
+    /// Shared with white-label-service (WHITE_LABEL_INTERNAL_API_TOKEN)
+    pub internal_api_token: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentConfig {
+    /// Generate missing invoice and receipt PDFs in worker mode
+    pub enabled: bool,
+    /// Minutes between checks for billing records without documents
+    pub generation_interval_minutes: u64,
+    /// File-service, where the PDFs are stored
+    pub file_service_url: String,
+    /// White-label-service, whose tenant branding is applied to documents
+    pub white_label_service_url: String,
+    /// Brand name on documents of tenants without white-label branding
+    pub default_brand_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxConfig {
+    /// Where invoice tax comes from: "manual" (the `tax_rates` tables),
+    /// "stripe" (Stripe Tax) or "taxjar"
+    pub provider: String,
+    /// Seller's address, which decides reverse charge and TaxJar's nexus.
+    /// Tenants without a tax profile are taxed as if they were here.
+    pub seller_country: String,
+    pub seller_region: String,
+    pub seller_postal_code: String,
+    pub taxjar_api_url: String,
+    pub taxjar_api_key: String,
+    /// VIES REST API, which confirms EU VAT numbers
+    pub vies_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrencyConfig {
+    /// Currencies tenants can be priced and billed in. Prices fall back to
+    /// `billing.default_currency`'s price book, converted at the latest rate.
+    pub supported: Vec<String>,
+    /// Frankfurter-compatible exchange rate API
+    pub exchange_rate_url: String,
+    /// Refresh exchange rates in worker mode
+    pub refresh_enabled: bool,
+    pub refresh_interval_minutes: u64,
 }
 
 impl Default for LicenseConfig {
@@ -62,11 +165,21 @@ impl Default for LicenseConfig {
             database_url: "postgresql://localhost:5432/adx_core".to_string(),
             redis_url: "redis://localhost:6379".to_string(),
             server_port: 8087,
+            grpc_port: 50087,
+            tenant_service_url: "http://localhost:8085".to_string(),
+            module_service_url: "http://localhost:8086".to_string(),
             temporal: TemporalConfig::default(),
             stripe: StripeConfig::default(),
             paypal: PayPalConfig::default(),
             billing: BillingConfig::default(),
             quotas: QuotaConfig::default(),
+            seats: SeatConfig::default(),
+            dunning: DunningConfig::default(),
+            grace: GraceConfig::default(),
+            notifications: NotificationConfig::default(),
+            documents: DocumentConfig::default(),
+            tax: TaxConfig::default(),
+            currencies: CurrencyConfig::default(),
         }
     }
 }
@@ -114,6 +227,7 @@ impl Default for BillingConfig {
             grace_period_days: 7,
             retry_failed_payments: true,
             max_payment_retries: 3,
+            developer_revenue_share: 0.7,
         }
     }
 }
@@ -126,10 +240,99 @@ impl Default for QuotaConfig {
             usage_aggregation_interval_seconds: 300, // 5 minutes
             warning_notification_enabled: true,
             auto_suspend_on_violation: false,
+            limit_cache_seconds: 60,
         }
     }
 }
 
+impl Default for SeatConfig {
+    fn default() -> Self {
+        Self {
+            reconciliation_enabled: true,
+            reconciliation_hour_utc: 2,
+            adjust_billing: true,
+        }
+    }
+}
+
+impl Default for DunningConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            check_interval_minutes: 15,
+            retry_interval_days: 3,
+            restricted_features: default_restricted_features(),
+        }
+    }
+}
+
+impl Default for GraceConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            period_days: 14,
+        }
+    }
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            white_label_service_url: "http://localhost:8089".to_string(),
+            internal_api_token: "".to_string(),
+        }
+    }
+}
+
+impl Default for DocumentConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            generation_interval_minutes: 10,
+            file_service_url: "http://localhost:8083".to_string(),
+            white_label_service_url: "http://localhost:8089".to_string(),
+            default_brand_name: "ADX Core".to_string(),
+        }
+    }
+}
+
+impl Default for TaxConfig {
+    fn default() -> Self {
+        Self {
+            provider: "manual".to_string(),
+            seller_country: "US".to_string(),
+            seller_region: "".to_string(),
+            seller_postal_code: "".to_string(),
+            taxjar_api_url: "https://api.taxjar.com".to_string(),
+            taxjar_api_key: "".to_string(),
+            vies_url: "https://ec.europa.eu/taxation_customs/vies/rest-api".to_string(),
+        }
+    }
+}
+
+impl Default for CurrencyConfig {
+    fn default() -> Self {
+        Self {
+            supported: default_supported_currencies(),
+            exchange_rate_url: "https://api.frankfurter.app".to_string(),
+            refresh_enabled: true,
+            refresh_interval_minutes: 360,
+        }
+    }
+}
+
+fn default_supported_currencies() -> Vec<String> {
+    ["USD", "EUR", "GBP", "CAD", "AUD"].iter().map(|currency| currency.to_string()).collect()
+}
+
+fn default_restricted_features() -> Vec<String> {
+    vec![
+        "custom_branding".to_string(),
+        "advanced_workflows".to_string(),
+        "api_access".to_string(),
+    ]
+}
+
 impl LicenseConfig {
     pub fn from_env() -> Result<Self, config::ConfigError> {
         let mut cfg = config::Config::builder()
@@ -138,6 +341,9 @@ impl LicenseConfig {
         
         // Set defaults
         cfg.set_default("server_port", 8087)?;
+        cfg.set_default("grpc_port", 50087)?;
+        cfg.set_default("tenant_service_url", "http://localhost:8085")?;
+        cfg.set_default("module_service_url", "http://localhost:8086")?;
         cfg.set_default("temporal.server_url", "http://localhost:7233")?;
         cfg.set_default("temporal.namespace", "default")?;
         cfg.set_default("temporal.task_queue", "license-service-queue")?;
@@ -149,11 +355,40 @@ impl LicenseConfig {
         cfg.set_default("billing.grace_period_days", 7)?;
         cfg.set_default("billing.retry_failed_payments", true)?;
         cfg.set_default("billing.max_payment_retries", 3)?;
+        cfg.set_default("billing.developer_revenue_share", 0.7)?;
         cfg.set_default("quotas.enforcement_enabled", true)?;
         cfg.set_default("quotas.real_time_monitoring", true)?;
         cfg.set_default("quotas.usage_aggregation_interval_seconds", 300)?;
         cfg.set_default("quotas.warning_notification_enabled", true)?;
         cfg.set_default("quotas.auto_suspend_on_violation", false)?;
+        cfg.set_default("quotas.limit_cache_seconds", 60)?;
+        cfg.set_default("seats.reconciliation_enabled", true)?;
+        cfg.set_default("seats.reconciliation_hour_utc", 2)?;
+        cfg.set_default("seats.adjust_billing", true)?;
+        cfg.set_default("dunning.enabled", true)?;
+        cfg.set_default("dunning.check_interval_minutes", 15)?;
+        cfg.set_default("dunning.retry_interval_days", 3)?;
+        cfg.set_default("dunning.restricted_features", default_restricted_features())?;
+        cfg.set_default("grace.enabled", true)?;
+        cfg.set_default("grace.period_days", 14)?;
+        cfg.set_default("notifications.white_label_service_url", "http://localhost:8089")?;
+        cfg.set_default("notifications.internal_api_token", "")?;
+        cfg.set_default("documents.enabled", true)?;
+        cfg.set_default("documents.generation_interval_minutes", 10)?;
+        cfg.set_default("documents.file_service_url", "http://localhost:8083")?;
+        cfg.set_default("documents.white_label_service_url", "http://localhost:8089")?;
+        cfg.set_default("documents.default_brand_name", "ADX Core")?;
+        cfg.set_default("tax.provider", "manual")?;
+        cfg.set_default("tax.seller_country", "US")?;
+        cfg.set_default("tax.seller_region", "")?;
+        cfg.set_default("tax.seller_postal_code", "")?;
+        cfg.set_default("tax.taxjar_api_url", "https://api.taxjar.com")?;
+        cfg.set_default("tax.taxjar_api_key", "")?;
+        cfg.set_default("tax.vies_url", "https://ec.europa.eu/taxation_customs/vies/rest-api")?;
+        cfg.set_default("currencies.supported", default_supported_currencies())?;
+        cfg.set_default("currencies.exchange_rate_url", "https://api.frankfurter.app")?;
+        cfg.set_default("currencies.refresh_enabled", true)?;
+        cfg.set_default("currencies.refresh_interval_minutes", 360)?;
         
         cfg.try_deserialize()
     }
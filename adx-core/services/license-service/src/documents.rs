@@ -0,0 +1,301 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+use crate::{
+    branding::DocumentBranding,
+    models::{BillingDocumentType, BillingHistory},
+};
+
+// A4, in points
+const PAGE_WIDTH: f32 = 595.0;
+const PAGE_HEIGHT: f32 = 842.0;
+const MARGIN: f32 = 50.0;
+
+const TEXT_COLOR: Rgb = Rgb(0.12, 0.12, 0.12);
+const MUTED_COLOR: Rgb = Rgb(0.45, 0.45, 0.45);
+const WHITE: Rgb = Rgb(1.0, 1.0, 1.0);
+/// Used when a tenant's branding colour isn't a "#rrggbb" hex colour
+const FALLBACK_COLOR: Rgb = Rgb(0.12, 0.16, 0.22);
+
+#[derive(Debug, Clone, Copy)]
+struct Rgb(f32, f32, f32);
+
+impl Rgb {
+    fn from_hex(hex: &str) -> Option<Self> {
+        let hex = hex.trim().trim_start_matches('#');
+        if hex.len() != 6 || !hex.is_ascii() {
+            return None;
+        }
+        let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok().map(|c| c as f32 / 255.0);
+        Some(Rgb(channel(0)?, channel(2)?, channel(4)?))
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+enum Font {
+    Regular,
+    Bold,
+}
+
+impl Font {
+    fn resource(&self) -> &'static str {
+        match self {
+            Font::Regular => "/F1",
+            Font::Bold => "/F2",
+        }
+    }
+}
+
+/// The number a billing record's document is issued under
+pub fn document_number(document_type: BillingDocumentType, record: &BillingHistory) -> String {
+    match document_type {
+        BillingDocumentType::Invoice => record.invoice_number.clone(),
+        BillingDocumentType::Receipt => format!("{}-R", record.invoice_number),
+    }
+}
+
+pub fn document_filename(document_type: BillingDocumentType, document_number: &str) -> String {
+    format!("{}-{}.pdf", document_type.as_str(), document_number)
+}
+
+/// Render an invoice or receipt of a billing record as a single-page PDF,
+/// with the tenant's brand name and colours in the header
+pub fn render_billing_document(
+    document_type: BillingDocumentType,
+    record: &BillingHistory,
+    document_number: &str,
+    issued_at: DateTime<Utc>,
+    branding: &DocumentBranding,
+) -> Vec<u8> {
+    let primary = Rgb::from_hex(&branding.primary_color).unwrap_or(FALLBACK_COLOR);
+    let accent = Rgb::from_hex(&branding.accent_color).unwrap_or(primary);
+    let mut page = Page::default();
+
+    // Header bar
+    page.fill_rect(0.0, PAGE_HEIGHT - 70.0, PAGE_WIDTH, 70.0, primary);
+    page.text(Font::Bold, 22.0, MARGIN, PAGE_HEIGHT - 45.0, WHITE, &branding.brand_name);
+
+    let title = match document_type {
+        BillingDocumentType::Invoice => "INVOICE",
+        BillingDocumentType::Receipt => "RECEIPT",
+    };
+    page.text(Font::Bold, 24.0, MARGIN, PAGE_HEIGHT - 120.0, accent, title);
+
+    let mut details = vec![
+        ("Number", document_number.to_string()),
+        ("Issued", format_date(issued_at)),
+    ];
+    if document_type == BillingDocumentType::Receipt {
+        details.push(("Invoice", record.invoice_number.clone()));
+    }
+    details.push((
+        "Billing period",
+        format!("{} to {}", format_date(record.billing_period_start), format_date(record.billing_period_end)),
+    ));
+    details.push(("Tenant", record.tenant_id.to_string()));
+    if document_type == BillingDocumentType::Receipt {
+        if let Some(paid_at) = record.paid_at {
+            details.push(("Paid", format_date(paid_at)));
+        }
+        if let Some(payment_method) = &record.payment_method {
+            details.push(("Payment method", payment_method.clone()));
+        }
+        if let Some(payment_reference) = &record.payment_reference {
+            details.push(("Payment reference", payment_reference.clone()));
+        }
+    }
+
+    let mut y = PAGE_HEIGHT - 155.0;
+    for (label, value) in &details {
+        page.text(Font::Regular, 10.0, MARGIN, y, MUTED_COLOR, label);
+        page.text(Font::Regular, 10.0, MARGIN + 120.0, y, TEXT_COLOR, value);
+        y -= 16.0;
+    }
+
+    // Charges
+    y -= 24.0;
+    let amount_right = PAGE_WIDTH - MARGIN;
+    page.text(Font::Bold, 10.0, MARGIN, y, TEXT_COLOR, "Description");
+    page.text_right(Font::Bold, 10.0, amount_right, y, TEXT_COLOR, "Amount");
+    y -= 8.0;
+    page.rule(MARGIN, amount_right, y, accent);
+    y -= 18.0;
+
+    let subtotal = record.amount - record.tax_amount;
+    page.text(Font::Regular, 10.0, MARGIN, y, TEXT_COLOR, &charge_description(record));
+    page.text_right(Font::Regular, 10.0, amount_right, y, TEXT_COLOR, &format_amount(subtotal, &record.currency));
+    y -= 10.0;
+    page.rule(MARGIN, amount_right, y, MUTED_COLOR);
+
+    let totals_left = amount_right - 200.0;
+    y -= 18.0;
+    page.text(Font::Regular, 10.0, totals_left, y, MUTED_COLOR, "Subtotal");
+    page.text_right(Font::Regular, 10.0, amount_right, y, TEXT_COLOR, &format_amount(subtotal, &record.currency));
+    y -= 16.0;
+    page.text(Font::Regular, 10.0, totals_left, y, MUTED_COLOR, "Tax");
+    page.text_right(Font::Regular, 10.0, amount_right, y, TEXT_COLOR, &format_amount(record.tax_amount, &record.currency));
+    y -= 20.0;
+    let total_label = match document_type {
+        BillingDocumentType::Receipt => "Amount paid",
+        BillingDocumentType::Invoice => "Total",
+    };
+    page.text(Font::Bold, 12.0, totals_left, y, TEXT_COLOR, total_label);
+    page.text_right(Font::Bold, 12.0, amount_right, y, accent, &format_amount(record.amount, &record.currency));
+
+    let footer = match document_type {
+        BillingDocumentType::Invoice => format!("{} - invoice {}", branding.brand_name, document_number),
+        BillingDocumentType::Receipt => format!("{} - thank you for your payment", branding.brand_name),
+    };
+    page.text(Font::Regular, 8.0, MARGIN, 40.0, MUTED_COLOR, &footer);
+
+    assemble(&page.content)
+}
+
+/// What the record charged for, from the details stored with it
+fn charge_description(record: &BillingHistory) -> String {
+    let details = record.usage_details.as_ref();
+    match details.and_then(|details| details["type"].as_str()) {
+        Some("plan_change_proration") => {
+            let tier = |key: &str| {
+                details
+                    .and_then(|details| details[key].as_str())
+                    .unwrap_or("current")
+                    .to_string()
+            };
+            format!("Plan change from {} to {} (prorated)", tier("from_tier"), tier("to_tier"))
+        }
+        _ => format!(
+            "Subscription, {} to {}",
+            format_date(record.billing_period_start),
+            format_date(record.billing_period_end)
+        ),
+    }
+}
+
+fn format_date(at: DateTime<Utc>) -> String {
+    at.format("%Y-%m-%d").to_string()
+}
+
+fn format_amount(amount: Decimal, currency: &str) -> String {
+    format!("{} {:.2}", currency.to_uppercase(), amount.round_dp(2))
+}
+
+/// Content stream of a page, in PDF operators
+#[derive(Default)]
+struct Page {
+    content: Vec<u8>,
+}
+
+impl Page {
+    fn fill_rect(&mut self, x: f32, y: f32, width: f32, height: f32, color: Rgb) {
+        self.op(format!(
+            "{:.3} {:.3} {:.3} rg {:.1} {:.1} {:.1} {:.1} re f\n",
+            color.0, color.1, color.2, x, y, width, height
+        ));
+    }
+
+    fn rule(&mut self, from_x: f32, to_x: f32, y: f32, color: Rgb) {
+        self.op(format!(
+            "{:.3} {:.3} {:.3} RG 0.5 w {:.1} {:.1} m {:.1} {:.1} l S\n",
+            color.0, color.1, color.2, from_x, y, to_x, y
+        ));
+    }
+
+    fn text(&mut self, font: Font, size: f32, x: f32, y: f32, color: Rgb, text: &str) {
+        self.op(format!(
+            "BT {} {:.1} Tf {:.3} {:.3} {:.3} rg {:.1} {:.1} Td (",
+            font.resource(), size, color.0, color.1, color.2, x, y
+        ));
+        self.content.extend(encode_text(text));
+        self.content.extend_from_slice(b") Tj ET\n");
+    }
+
+    /// Text ending at `right_x`
+    fn text_right(&mut self, font: Font, size: f32, right_x: f32, y: f32, color: Rgb, text: &str) {
+        let x = right_x - text_width(font, size, text);
+        self.text(font, size, x, y, color, text);
+    }
+
+    fn op(&mut self, op: String) {
+        self.content.extend_from_slice(op.as_bytes());
+    }
+}
+
+/// Width of text set in Helvetica. Exact for the digits, punctuation and
+/// capitals amounts are made of; other characters are approximated.
+fn text_width(font: Font, size: f32, text: &str) -> f32 {
+    let units: u32 = text
+        .chars()
+        .map(|c| match (font, c) {
+            (_, '0'..='9') => 556,
+            (_, '.' | ',' | ' ') => 278,
+            (_, '-') => 333,
+            (Font::Regular, 'A'..='Z') => 667,
+            (Font::Bold, 'A'..='Z') => 722,
+            (Font::Regular, _) => 556,
+            (Font::Bold, _) => 611,
+        })
+        .sum();
+    units as f32 * size / 1000.0
+}
+
+/// Encode text as a WinAnsi PDF string body. Characters the standard
+/// fonts can't show are replaced with '?'.
+fn encode_text(text: &str) -> Vec<u8> {
+    let mut encoded = Vec::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '(' | ')' | '\\' => {
+                encoded.push(b'\\');
+                encoded.push(c as u8);
+            }
+            ' '..='~' => encoded.push(c as u8),
+            '\u{20ac}' => encoded.push(0x80),
+            '\u{2013}' => encoded.push(0x96),
+            '\u{2014}' => encoded.push(0x97),
+            '\u{a0}'..='\u{ff}' => encoded.push(c as u32 as u8),
+            _ => encoded.push(b'?'),
+        }
+    }
+    encoded
+}
+
+/// Wrap a page's content stream in a complete PDF document
+fn assemble(content: &[u8]) -> Vec<u8> {
+    let objects: Vec<Vec<u8>> = vec![
+        b"<< /Type /Catalog /Pages 2 0 R >>".to_vec(),
+        b"<< /Type /Pages /Kids [3 0 R] /Count 1 >>".to_vec(),
+        format!(
+            "<< /Type /Page /Parent 2 0 R /MediaBox [0 0 {} {}] /Resources << /Font << /F1 4 0 R /F2 5 0 R >> >> /Contents 6 0 R >>",
+            PAGE_WIDTH, PAGE_HEIGHT
+        )
+        .into_bytes(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica /Encoding /WinAnsiEncoding >>".to_vec(),
+        b"<< /Type /Font /Subtype /Type1 /BaseFont /Helvetica-Bold /Encoding /WinAnsiEncoding >>".to_vec(),
+        [format!("<< /Length {} >>\nstream\n", content.len()).as_bytes(), content, &b"\nendstream"[..]].concat(),
+    ];
+
+    let mut pdf = b"%PDF-1.4\n%\xe2\xe3\xcf\xd3\n".to_vec();
+    let mut offsets = Vec::with_capacity(objects.len());
+    for (i, object) in objects.iter().enumerate() {
+        offsets.push(pdf.len());
+        pdf.extend_from_slice(format!("{} 0 obj\n", i + 1).as_bytes());
+        pdf.extend_from_slice(object);
+        pdf.extend_from_slice(b"\nendobj\n");
+    }
+
+    let xref_offset = pdf.len();
+    pdf.extend_from_slice(format!("xref\n0 {}\n0000000000 65535 f \n", objects.len() + 1).as_bytes());
+    for offset in offsets {
+        pdf.extend_from_slice(format!("{:010} 00000 n \n", offset).as_bytes());
+    }
+    pdf.extend_from_slice(
+        format!(
+            "trailer\n<< /Size {} /Root 1 0 R >>\nstartxref\n{}\n%%EOF\n",
+            objects.len() + 1,
+            xref_offset
+        )
+        .as_bytes(),
+    );
+    pdf
+}
@@ -40,6 +40,33 @@ pub enum LicenseError {
     #[error("Subscription not found: {0}")]
     SubscriptionNotFound(String),
     
+    #[error("Dunning case not found: {0}")]
+    DunningCaseNotFound(String),
+    
+    #[error("Webhook event not found: {0}")]
+    WebhookEventNotFound(String),
+    
+    #[error("Billing record not found: {0}")]
+    BillingRecordNotFound(String),
+    
+    #[error("Billing document not found: {0}")]
+    BillingDocumentNotFound(String),
+    
+    #[error("Coupon not found: {0}")]
+    CouponNotFound(String),
+    
+    #[error("Coupon cannot be redeemed: {0}")]
+    InvalidCoupon(String),
+    
+    #[error("Tax profile not found: {0}")]
+    TaxProfileNotFound(String),
+    
+    #[error("Tax calculation error: {0}")]
+    TaxError(String),
+    
+    #[error("Exchange rate not found: {0}")]
+    ExchangeRateNotFound(String),
+    
     #[error("Configuration error: {0}")]
     ConfigError(String),
     
@@ -58,6 +85,9 @@ pub enum LicenseError {
     #[error("HTTP client error: {0}")]
     HttpError(#[from] reqwest::Error),
     
+    #[error("Redis error: {0}")]
+    Redis(#[from] redis::RedisError),
+    
     #[error("Internal error: {0}")]
     Internal(String),
 }
@@ -68,7 +98,8 @@ impl LicenseError {
             self,
             LicenseError::Database(_) |
             LicenseError::HttpError(_) |
-            LicenseError::PaymentError(_)
+            LicenseError::PaymentError(_) |
+            LicenseError::TaxError(_)
         )
     }
     
@@ -85,12 +116,22 @@ impl LicenseError {
             LicenseError::BillingError(_) => "BILLING_ERROR",
             LicenseError::InvalidLicenseKey(_) => "INVALID_LICENSE_KEY",
             LicenseError::SubscriptionNotFound(_) => "SUBSCRIPTION_NOT_FOUND",
+            LicenseError::DunningCaseNotFound(_) => "DUNNING_CASE_NOT_FOUND",
+            LicenseError::WebhookEventNotFound(_) => "WEBHOOK_EVENT_NOT_FOUND",
+            LicenseError::BillingRecordNotFound(_) => "BILLING_RECORD_NOT_FOUND",
+            LicenseError::BillingDocumentNotFound(_) => "BILLING_DOCUMENT_NOT_FOUND",
+            LicenseError::CouponNotFound(_) => "COUPON_NOT_FOUND",
+            LicenseError::InvalidCoupon(_) => "INVALID_COUPON",
+            LicenseError::TaxProfileNotFound(_) => "TAX_PROFILE_NOT_FOUND",
+            LicenseError::TaxError(_) => "TAX_ERROR",
+            LicenseError::ExchangeRateNotFound(_) => "EXCHANGE_RATE_NOT_FOUND",
             LicenseError::ConfigError(_) => "CONFIG_ERROR",
             LicenseError::ValidationError(_) => "VALIDATION_ERROR",
             LicenseError::WorkflowError(_) => "WORKFLOW_ERROR",
             LicenseError::ActivityError(_) => "ACTIVITY_ERROR",
             LicenseError::SerializationError(_) => "SERIALIZATION_ERROR",
             LicenseError::HttpError(_) => "HTTP_ERROR",
+            LicenseError::Redis(_) => "REDIS_ERROR",
             LicenseError::Internal(_) => "INTERNAL_ERROR",
         }
     }
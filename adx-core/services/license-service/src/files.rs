@@ -0,0 +1,107 @@
+use chrono::{DateTime, Utc};
+use reqwest::multipart;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::error::{LicenseError, Result};
+
+#[derive(Debug, Serialize)]
+struct CreateFile<'a> {
+    filename: &'a str,
+    mime_type: &'a str,
+    file_size: i64,
+    metadata: &'a serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct CreatedFile {
+    file_id: Uuid,
+}
+
+#[derive(Debug, Deserialize)]
+struct FileDownload {
+    download_url: String,
+    expires_at: DateTime<Utc>,
+}
+
+/// Stores billing documents in file-service. Documents belong to the tenant
+/// rather than to one of its users, so they are owned by the nil user.
+#[derive(Debug, Clone)]
+pub struct FileServiceClient {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl FileServiceClient {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Store `content` as a new file of the tenant's, returning its id
+    pub async fn upload(
+        &self,
+        tenant_id: Uuid,
+        filename: &str,
+        mime_type: &str,
+        content: Vec<u8>,
+        metadata: &serde_json::Value,
+    ) -> Result<Uuid> {
+        let response = self.client
+            .post(&format!("{}/api/v1/files", self.base_url))
+            .header("X-Tenant-ID", tenant_id.to_string())
+            .header("X-User-ID", Uuid::nil().to_string())
+            .json(&CreateFile {
+                filename,
+                mime_type,
+                file_size: content.len() as i64,
+                metadata,
+            })
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(LicenseError::Internal(format!("File service rejected {}: {}", filename, error_text)));
+        }
+        let created: CreatedFile = response.json().await?;
+
+        let part = multipart::Part::bytes(content)
+            .file_name(filename.to_string())
+            .mime_str(mime_type)?;
+        let response = self.client
+            .post(&format!("{}/api/v1/files/{}/upload", self.base_url, created.file_id))
+            .header("X-Tenant-ID", tenant_id.to_string())
+            .header("X-User-ID", Uuid::nil().to_string())
+            .multipart(multipart::Form::new().part("file", part))
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(created.file_id)
+        } else {
+            let error_text = response.text().await?;
+            Err(LicenseError::Internal(format!("File service upload of {} failed: {}", filename, error_text)))
+        }
+    }
+
+    /// A short-lived URL the file can be downloaded from, and when it expires
+    pub async fn download_url(&self, tenant_id: Uuid, file_id: Uuid) -> Result<(String, DateTime<Utc>)> {
+        let response = self.client
+            .get(&format!("{}/api/v1/files/{}/download", self.base_url, file_id))
+            .header("X-Tenant-ID", tenant_id.to_string())
+            .header("X-User-ID", Uuid::nil().to_string())
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(LicenseError::Internal(format!("File service download of {} failed: {}", file_id, error_text)));
+        }
+
+        let download: FileDownload = response.json().await?;
+        Ok((download.download_url, download.expires_at))
+    }
+}
@@ -0,0 +1,135 @@
+// Internal gRPC API
+//
+// Serves quota checks and seat changes to the other services on
+// `grpc_port`, alongside the REST API. Both call the same LicenseService
+// methods; gRPC only saves the JSON round trip on the per-request quota path.
+
+use adx_proto::quota::v1::{self as proto, quota_service_server::QuotaService};
+use adx_shared::telemetry::grpc_span;
+use tonic::{Request, Response, Status};
+use tracing::Instrument;
+use uuid::Uuid;
+
+use crate::models::{AssignSeatRequest, ConsumeQuotaRequest, QuotaCheckResult, UnassignSeatRequest};
+use crate::services::LicenseService;
+use crate::LicenseError;
+
+pub use proto::quota_service_server::QuotaServiceServer;
+
+#[derive(Clone)]
+pub struct QuotaGrpcService {
+    license_service: LicenseService,
+}
+
+impl QuotaGrpcService {
+    pub fn new(license_service: LicenseService) -> Self {
+        Self { license_service }
+    }
+
+    pub fn into_server(self) -> QuotaServiceServer<Self> {
+        QuotaServiceServer::new(self)
+    }
+}
+
+#[tonic::async_trait]
+impl QuotaService for QuotaGrpcService {
+    async fn check_quota(&self, request: Request<proto::QuotaRequest>) -> Result<Response<proto::QuotaCheck>, Status> {
+        let span = grpc_span("adx.quota.v1.QuotaService/CheckQuota", &request);
+        let request = request.into_inner();
+        let tenant_id = parse_id("tenant_id", &request.tenant_id)?;
+
+        let result = self
+            .license_service
+            .check_quota(tenant_id, &request.quota_name, request.amount)
+            .instrument(span)
+            .await
+            .map_err(status)?;
+        Ok(Response::new(quota_check(result)))
+    }
+
+    async fn consume_quota(&self, request: Request<proto::QuotaRequest>) -> Result<Response<proto::QuotaCheck>, Status> {
+        let span = grpc_span("adx.quota.v1.QuotaService/ConsumeQuota", &request);
+        let request = consume_request(request.into_inner())?;
+
+        let result = self.license_service.consume_quota(request).instrument(span).await.map_err(status)?;
+        Ok(Response::new(quota_check(result)))
+    }
+
+    async fn release_quota(&self, request: Request<proto::QuotaRequest>) -> Result<Response<proto::QuotaCheck>, Status> {
+        let span = grpc_span("adx.quota.v1.QuotaService/ReleaseQuota", &request);
+        let request = consume_request(request.into_inner())?;
+
+        let result = self.license_service.release_quota(request).instrument(span).await.map_err(status)?;
+        Ok(Response::new(quota_check(result)))
+    }
+
+    async fn assign_seat(
+        &self,
+        request: Request<proto::AssignSeatRequest>,
+    ) -> Result<Response<proto::AssignSeatResponse>, Status> {
+        let span = grpc_span("adx.quota.v1.QuotaService/AssignSeat", &request);
+        let request = request.into_inner();
+        let request = AssignSeatRequest {
+            tenant_id: parse_id("tenant_id", &request.tenant_id)?,
+            user_id: parse_id("user_id", &request.user_id)?,
+            assigned_by: request
+                .assigned_by
+                .as_deref()
+                .map(|assigned_by| parse_id("assigned_by", assigned_by))
+                .transpose()?,
+        };
+
+        self.license_service.assign_seat(request).instrument(span).await.map_err(status)?;
+        Ok(Response::new(proto::AssignSeatResponse {}))
+    }
+
+    async fn unassign_seat(
+        &self,
+        request: Request<proto::UnassignSeatRequest>,
+    ) -> Result<Response<proto::UnassignSeatResponse>, Status> {
+        let span = grpc_span("adx.quota.v1.QuotaService/UnassignSeat", &request);
+        let request = request.into_inner();
+        let request = UnassignSeatRequest {
+            tenant_id: parse_id("tenant_id", &request.tenant_id)?,
+            user_id: parse_id("user_id", &request.user_id)?,
+        };
+
+        let released = self.license_service.unassign_seat(request).instrument(span).await.map_err(status)?;
+        Ok(Response::new(proto::UnassignSeatResponse { released }))
+    }
+}
+
+fn parse_id(field: &str, value: &str) -> Result<Uuid, Status> {
+    Uuid::parse_str(value).map_err(|_| Status::invalid_argument(format!("{} must be a UUID", field)))
+}
+
+fn consume_request(request: proto::QuotaRequest) -> Result<ConsumeQuotaRequest, Status> {
+    Ok(ConsumeQuotaRequest {
+        tenant_id: parse_id("tenant_id", &request.tenant_id)?,
+        quota_name: request.quota_name,
+        amount: request.amount,
+    })
+}
+
+fn quota_check(result: QuotaCheckResult) -> proto::QuotaCheck {
+    proto::QuotaCheck {
+        quota_name: result.quota_name,
+        allowed: result.allowed,
+        current_usage: result.current_usage,
+        quota_limit: result.quota_limit,
+        remaining: result.remaining,
+    }
+}
+
+/// Same mapping as the REST handlers' status codes
+fn status(error: LicenseError) -> Status {
+    match error {
+        LicenseError::ValidationError(message) => Status::invalid_argument(message),
+        LicenseError::QuotaNotFound { quota_name } => Status::not_found(format!("Quota not found: {}", quota_name)),
+        error @ LicenseError::QuotaExceeded { .. } => Status::resource_exhausted(error.to_string()),
+        error => {
+            tracing::error!("Internal gRPC call failed: {:?}", error);
+            Status::internal(error.to_string())
+        }
+    }
+}
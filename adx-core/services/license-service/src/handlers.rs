@@ -1,6 +1,6 @@
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
+    http::{HeaderMap, StatusCode},
     response::Json,
     routing::{get, post, put, delete},
     Router,
@@ -10,6 +10,7 @@ use serde::{Deserialize, Serialize};
 use uuid::Uuid;
 
 use crate::{
+    billing::PayPalWebhookHeaders,
     error::{LicenseError, Result},
     models::*,
     services::LicenseService,
@@ -38,12 +39,57 @@ pub struct PaginationQuery {
     pub offset: Option<i64>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct DunningQuery {
+    pub status: Option<DunningStatus>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct WebhookEventQuery {
+    pub provider: Option<String>,
+    pub status: Option<WebhookEventStatus>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CouponQuery {
+    pub active_only: Option<bool>,
+    pub limit: Option<i64>,
+    pub offset: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct TaxRateQuery {
+    pub country: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PlanPriceQuery {
+    pub tenant_id: Option<Uuid>,
+    pub currency: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct PriceBookQuery {
+    pub currency: Option<String>,
+}
+
 #[derive(Debug, Deserialize)]
 pub struct DateRangeQuery {
     pub start_date: Option<DateTime<Utc>>,
     pub end_date: Option<DateTime<Utc>>,
 }
 
+#[derive(Debug, Deserialize)]
+pub struct UsageRollupRequest {
+    pub tenant_ids: Vec<Uuid>,
+    pub start_date: Option<DateTime<Utc>>,
+    pub end_date: Option<DateTime<Utc>>,
+}
+
 // Application state
 #[derive(Clone)]
 pub struct AppState {
@@ -61,17 +107,82 @@ pub fn create_router(state: AppState) -> Router {
         .route("/licenses/validate/:license_key", get(validate_license_handler))
         .route("/licenses/expiring", get(get_expiring_licenses_handler))
         
+        // Subscription change routes
+        .route("/subscriptions/preview", post(preview_subscription_change_handler))
+        
+        // Entitlement routes
+        .route("/api/v1/entitlements/:tenant_id", get(get_entitlements_handler))
+        
         // Quota management routes
+        .route("/quotas/definitions", get(get_quota_definitions_handler))
+        .route("/quotas/definitions", post(create_quota_definition_handler))
+        .route("/quotas/definitions/:name", put(update_quota_definition_handler))
         .route("/quotas/tenant/:tenant_id", get(get_tenant_quotas_handler))
         .route("/quotas/tenant/:tenant_id/summary", get(get_quota_usage_summary_handler))
         .route("/quotas/check", post(check_quota_handler))
         .route("/quotas/enforce", post(enforce_quota_handler))
         .route("/quotas/reset", post(reset_quota_handler))
+        .route("/quotas/consume", post(consume_quota_handler))
+        .route("/quotas/release", post(release_quota_handler))
+        
+        // Seat management routes
+        .route("/seats/tenant/:tenant_id", get(get_seat_summary_handler))
+        .route("/seats/tenant/:tenant_id/reconciliations", get(get_seat_reconciliations_handler))
+        .route("/seats/assign", post(assign_seat_handler))
+        .route("/seats/unassign", post(unassign_seat_handler))
         
         // Billing routes
         .route("/billing/tenant/:tenant_id", get(get_billing_history_handler))
         .route("/billing/invoice", post(generate_invoice_handler))
         .route("/billing/:id/status", put(update_payment_status_handler))
+        .route("/billing/:id/documents", post(generate_billing_documents_handler))
+        .route("/billing/tenant/:tenant_id/documents", get(list_billing_documents_handler))
+        .route("/billing/tenant/:tenant_id/documents/:document_id/download", get(get_billing_document_download_handler))
+        .route("/billing/tenant/:tenant_id/invoices/:invoice_number/tax", get(get_invoice_tax_lines_handler))
+        .route("/billing/tenant/:tenant_id/invoices/:invoice_number/exchange-rate", get(get_invoice_exchange_rate_handler))
+        .route("/billing/module-usage", post(record_module_usage_handler))
+        .route("/billing/developers/:developer_id/revenue", get(get_developer_revenue_handler))
+        .route("/billing/rollup", post(get_usage_rollup_handler))
+        
+        // Dunning routes
+        .route("/dunning", get(list_dunning_cases_handler))
+        .route("/dunning/:id", get(get_dunning_case_handler))
+        .route("/dunning/tenant/:tenant_id", get(get_tenant_dunning_cases_handler))
+        .route("/dunning/:id/pause", post(pause_dunning_case_handler))
+        .route("/dunning/:id/resume", post(resume_dunning_case_handler))
+        .route("/dunning/:id/forgive", post(forgive_dunning_case_handler))
+        
+        // Webhook routes
+        .route("/webhooks/stripe", post(stripe_webhook_handler))
+        .route("/webhooks/paypal", post(paypal_webhook_handler))
+        .route("/webhooks/events", get(list_webhook_events_handler))
+        .route("/webhooks/events/:id", get(get_webhook_event_handler))
+        .route("/webhooks/events/:id/replay", post(replay_webhook_event_handler))
+        
+        // Tax routes
+        .route("/tax/tenant/:tenant_id/profile", get(get_tax_profile_handler))
+        .route("/tax/tenant/:tenant_id/profile", put(upsert_tax_profile_handler))
+        .route("/tax/tenant/:tenant_id/profile/validate", post(validate_tax_id_handler))
+        .route("/tax/rates", get(list_tax_rates_handler))
+        .route("/tax/rates", post(create_tax_rate_handler))
+        
+        // Pricing routes
+        .route("/pricing/plans", get(list_plan_prices_handler))
+        .route("/pricing/price-book", get(list_price_book_handler))
+        .route("/pricing/price-book", put(upsert_price_book_entry_handler))
+        .route("/pricing/tenant/:tenant_id/currency", get(get_tenant_currency_handler))
+        .route("/pricing/tenant/:tenant_id/currency", put(set_tenant_currency_handler))
+        .route("/pricing/exchange-rates", get(list_exchange_rates_handler))
+        .route("/pricing/exchange-rates", post(record_exchange_rate_handler))
+        
+        // Promotion routes
+        .route("/promotions/coupons", post(create_coupon_handler))
+        .route("/promotions/coupons", get(list_coupons_handler))
+        .route("/promotions/coupons/:code", put(update_coupon_handler))
+        .route("/promotions/coupons/:code/redemptions", get(get_coupon_redemptions_handler))
+        .route("/promotions/validate", post(validate_promotion_handler))
+        .route("/promotions/credits", post(grant_credit_handler))
+        .route("/promotions/credits/tenant/:tenant_id", get(get_tenant_credits_handler))
         
         // Compliance routes
         .route("/compliance/tenant/:tenant_id/logs", get(get_compliance_logs_handler))
@@ -82,6 +193,9 @@ pub fn create_router(state: AppState) -> Router {
         .route("/workflows/provision-license", post(provision_license_workflow_handler))
         .route("/workflows/enforce-quota", post(enforce_quota_workflow_handler))
         .route("/workflows/renew-license", post(renew_license_workflow_handler))
+        .route("/workflows/reconcile-seats", post(reconcile_seats_workflow_handler))
+        .route("/workflows/change-subscription", post(change_subscription_workflow_handler))
+        .route("/workflows/dunning", post(dunning_workflow_handler))
         
         // Analytics routes
         .route("/analytics/tenant/:tenant_id", get(get_license_analytics_handler))
@@ -209,6 +323,50 @@ async fn get_expiring_licenses_handler(
     }
 }
 
+// Subscription change handlers
+async fn preview_subscription_change_handler(
+    State(state): State<AppState>,
+    Json(request): Json<SubscriptionChangeRequest>,
+) -> Result<Json<ApiResponse<ProrationPreview>>, StatusCode> {
+    match state.license_service.preview_subscription_change(request).await {
+        Ok(preview) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(preview),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::LicenseNotFound(_)) => Err(StatusCode::NOT_FOUND),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(e) => {
+            tracing::error!("Failed to preview subscription change: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Entitlement handlers
+async fn get_entitlements_handler(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Entitlements>>, StatusCode> {
+    match state.license_service.get_entitlements(tenant_id).await {
+        Ok(entitlements) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(entitlements),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::HttpError(e)) => {
+            tracing::warn!("Module licenses unavailable: {}", e);
+            Err(StatusCode::BAD_GATEWAY)
+        }
+        Err(e) => {
+            tracing::error!("Failed to get entitlements: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 // Quota handlers
 async fn get_tenant_quotas_handler(
     State(state): State<AppState>,
@@ -305,6 +463,184 @@ async fn reset_quota_handler(
     }
 }
 
+async fn consume_quota_handler(
+    State(state): State<AppState>,
+    Json(request): Json<ConsumeQuotaRequest>,
+) -> Result<Json<ApiResponse<QuotaCheckResult>>, StatusCode> {
+    match state.license_service.consume_quota(request).await {
+        Ok(result) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(result),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(LicenseError::QuotaNotFound { .. }) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to consume quota: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn release_quota_handler(
+    State(state): State<AppState>,
+    Json(request): Json<ConsumeQuotaRequest>,
+) -> Result<Json<ApiResponse<QuotaCheckResult>>, StatusCode> {
+    match state.license_service.release_quota(request).await {
+        Ok(result) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(result),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(LicenseError::QuotaNotFound { .. }) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to release quota: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn get_quota_definitions_handler(
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<Vec<QuotaDefinition>>>, StatusCode> {
+    match state.license_service.get_quota_definitions().await {
+        Ok(definitions) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(definitions),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to get quota definitions: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn create_quota_definition_handler(
+    State(state): State<AppState>,
+    Json(request): Json<CreateQuotaDefinitionRequest>,
+) -> Result<Json<ApiResponse<QuotaDefinition>>, StatusCode> {
+    match state.license_service.create_quota_definition(request).await {
+        Ok(definition) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(definition),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(LicenseError::Database(sqlx::Error::Database(e))) if e.is_unique_violation() => Err(StatusCode::CONFLICT),
+        Err(e) => {
+            tracing::error!("Failed to create quota definition: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn update_quota_definition_handler(
+    State(state): State<AppState>,
+    Path(name): Path<String>,
+    Json(request): Json<UpdateQuotaDefinitionRequest>,
+) -> Result<Json<ApiResponse<QuotaDefinition>>, StatusCode> {
+    match state.license_service.update_quota_definition(&name, request).await {
+        Ok(definition) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(definition),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(LicenseError::QuotaNotFound { .. }) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to update quota definition: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Seat management handlers
+async fn get_seat_summary_handler(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<SeatSummary>>, StatusCode> {
+    match state.license_service.get_seat_summary(tenant_id).await {
+        Ok(summary) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(summary),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::QuotaNotFound { .. }) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to get seat summary: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn get_seat_reconciliations_handler(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<Uuid>,
+    Query(pagination): Query<PaginationQuery>,
+) -> Result<Json<ApiResponse<Vec<SeatReconciliation>>>, StatusCode> {
+    let limit = pagination.limit.unwrap_or(30);
+
+    match state.license_service.get_seat_reconciliations(tenant_id, limit).await {
+        Ok(reconciliations) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(reconciliations),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to get seat reconciliations: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn assign_seat_handler(
+    State(state): State<AppState>,
+    Json(request): Json<AssignSeatRequest>,
+) -> Result<Json<ApiResponse<SeatAssignment>>, StatusCode> {
+    match state.license_service.assign_seat(request).await {
+        Ok(assignment) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(assignment),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::QuotaExceeded { .. }) => Err(StatusCode::TOO_MANY_REQUESTS),
+        Err(LicenseError::QuotaNotFound { .. }) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to assign seat: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn unassign_seat_handler(
+    State(state): State<AppState>,
+    Json(request): Json<UnassignSeatRequest>,
+) -> Result<Json<ApiResponse<()>>, StatusCode> {
+    match state.license_service.unassign_seat(request).await {
+        Ok(true) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(()),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Ok(false) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to unassign seat: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 // Billing handlers
 async fn get_billing_history_handler(
     State(state): State<AppState>,
@@ -339,6 +675,11 @@ async fn generate_invoice_handler(
             error: None,
             timestamp: Utc::now(),
         })),
+        Err(LicenseError::LicenseNotFound(_)) => Err(StatusCode::NOT_FOUND),
+        Err(LicenseError::TaxError(e)) => {
+            tracing::error!("Failed to calculate invoice tax: {}", e);
+            Err(StatusCode::BAD_GATEWAY)
+        }
         Err(e) => {
             tracing::error!("Failed to generate invoice: {:?}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
@@ -346,57 +687,71 @@ async fn generate_invoice_handler(
     }
 }
 
-async fn update_payment_status_handler(
+async fn get_invoice_tax_lines_handler(
     State(state): State<AppState>,
-    Path(id): Path<Uuid>,
-    Json(request): Json<UpdatePaymentStatusRequest>,
-) -> Result<Json<ApiResponse<()>>, StatusCode> {
-    match state.license_service.update_payment_status(id, request.status, request.payment_reference).await {
-        Ok(_) => Ok(Json(ApiResponse {
+    Path((tenant_id, invoice_number)): Path<(Uuid, String)>,
+) -> Result<Json<ApiResponse<Vec<InvoiceTaxLine>>>, StatusCode> {
+    match state.license_service.get_invoice_tax_lines(tenant_id, &invoice_number).await {
+        Ok(lines) => Ok(Json(ApiResponse {
             success: true,
-            data: Some(()),
+            data: Some(lines),
             error: None,
             timestamp: Utc::now(),
         })),
         Err(e) => {
-            tracing::error!("Failed to update payment status: {:?}", e);
+            tracing::error!("Failed to get invoice tax lines: {:?}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
-// Compliance handlers
-async fn get_compliance_logs_handler(
+async fn get_invoice_exchange_rate_handler(
     State(state): State<AppState>,
-    Path(tenant_id): Path<Uuid>,
-    Query(query): Query<DateRangeQuery>,
-) -> Result<Json<ApiResponse<Vec<ComplianceLog>>>, StatusCode> {
-    let start_date = query.start_date.unwrap_or_else(|| Utc::now() - chrono::Duration::days(30));
-    let end_date = query.end_date.unwrap_or_else(Utc::now);
-    
-    match state.license_service.get_compliance_logs(tenant_id, start_date, end_date).await {
-        Ok(logs) => Ok(Json(ApiResponse {
+    Path((tenant_id, invoice_number)): Path<(Uuid, String)>,
+) -> Result<Json<ApiResponse<InvoiceExchangeRate>>, StatusCode> {
+    match state.license_service.get_invoice_exchange_rate(tenant_id, &invoice_number).await {
+        Ok(rate) => Ok(Json(ApiResponse {
             success: true,
-            data: Some(logs),
+            data: Some(rate),
             error: None,
             timestamp: Utc::now(),
         })),
+        Err(LicenseError::ExchangeRateNotFound(_)) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
-            tracing::error!("Failed to get compliance logs: {:?}", e);
+            tracing::error!("Failed to get invoice exchange rate: {:?}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
-async fn generate_compliance_report_handler(
+async fn record_module_usage_handler(
     State(state): State<AppState>,
-    Path(tenant_id): Path<Uuid>,
+    Json(request): Json<RecordModuleUsageRequest>,
+) -> Result<Json<ApiResponse<u64>>, StatusCode> {
+    match state.license_service.record_module_usage(request).await {
+        Ok(recorded) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(recorded),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(e) => {
+            tracing::error!("Failed to record module usage: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn get_developer_revenue_handler(
+    State(state): State<AppState>,
+    Path(developer_id): Path<String>,
     Query(query): Query<DateRangeQuery>,
-) -> Result<Json<ApiResponse<ComplianceReport>>, StatusCode> {
+) -> Result<Json<ApiResponse<DeveloperRevenueReport>>, StatusCode> {
     let start_date = query.start_date.unwrap_or_else(|| Utc::now() - chrono::Duration::days(30));
     let end_date = query.end_date.unwrap_or_else(Utc::now);
     
-    match state.license_service.generate_compliance_report(tenant_id, start_date, end_date).await {
+    match state.license_service.get_developer_revenue(&developer_id, start_date, end_date).await {
         Ok(report) => Ok(Json(ApiResponse {
             success: true,
             data: Some(report),
@@ -404,79 +759,819 @@ async fn generate_compliance_report_handler(
             timestamp: Utc::now(),
         })),
         Err(e) => {
-            tracing::error!("Failed to generate compliance report: {:?}", e);
+            tracing::error!("Failed to get developer revenue: {:?}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
-async fn resolve_compliance_issue_handler(
+async fn get_usage_rollup_handler(
     State(state): State<AppState>,
-    Path(id): Path<Uuid>,
-    Json(request): Json<ResolveComplianceIssueRequest>,
-) -> Result<Json<ApiResponse<()>>, StatusCode> {
-    match state.license_service.resolve_compliance_issue(id, request.resolved_by, request.resolution_notes).await {
-        Ok(_) => Ok(Json(ApiResponse {
+    Json(request): Json<UsageRollupRequest>,
+) -> Result<Json<ApiResponse<crate::services::UsageRollupReport>>, StatusCode> {
+    let start_date = request.start_date.unwrap_or_else(|| Utc::now() - chrono::Duration::days(30));
+    let end_date = request.end_date.unwrap_or_else(Utc::now);
+
+    match state.license_service.get_usage_rollup(&request.tenant_ids, start_date, end_date).await {
+        Ok(report) => Ok(Json(ApiResponse {
             success: true,
-            data: Some(()),
+            data: Some(report),
             error: None,
             timestamp: Utc::now(),
         })),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::BAD_REQUEST),
         Err(e) => {
-            tracing::error!("Failed to resolve compliance issue: {:?}", e);
+            tracing::error!("Failed to get usage roll-up: {:?}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
-// Workflow handlers
-async fn provision_license_workflow_handler(
+async fn update_payment_status_handler(
     State(state): State<AppState>,
-    Json(request): Json<LicenseProvisioningWorkflowRequest>,
-) -> Result<Json<ApiResponse<WorkflowResponse>>, StatusCode> {
-    match state.license_service.initiate_license_provisioning(request).await {
-        Ok(workflow_id) => Ok(Json(ApiResponse {
+    Path(id): Path<Uuid>,
+    Json(request): Json<UpdatePaymentStatusRequest>,
+) -> Result<Json<ApiResponse<()>>, StatusCode> {
+    match state.license_service.update_payment_status(id, request.status, request.payment_reference).await {
+        Ok(_) => Ok(Json(ApiResponse {
             success: true,
-            data: Some(WorkflowResponse {
-                workflow_id,
-                status: "started".to_string(),
-                message: "License provisioning workflow initiated".to_string(),
-            }),
+            data: Some(()),
             error: None,
             timestamp: Utc::now(),
         })),
         Err(e) => {
-            tracing::error!("Failed to start license provisioning workflow: {:?}", e);
+            tracing::error!("Failed to update payment status: {:?}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
-async fn enforce_quota_workflow_handler(
+async fn generate_billing_documents_handler(
     State(state): State<AppState>,
-    Json(request): Json<QuotaEnforcementWorkflowRequest>,
-) -> Result<Json<ApiResponse<WorkflowResponse>>, StatusCode> {
-    match state.license_service.initiate_quota_enforcement(request).await {
-        Ok(workflow_id) => Ok(Json(ApiResponse {
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<BillingDocument>>>, StatusCode> {
+    match state.license_service.generate_billing_documents(id).await {
+        Ok(documents) => Ok(Json(ApiResponse {
             success: true,
-            data: Some(WorkflowResponse {
-                workflow_id,
-                status: "started".to_string(),
-                message: "Quota enforcement workflow initiated".to_string(),
-            }),
+            data: Some(documents),
             error: None,
             timestamp: Utc::now(),
         })),
+        Err(LicenseError::BillingRecordNotFound(_)) => Err(StatusCode::NOT_FOUND),
         Err(e) => {
-            tracing::error!("Failed to start quota enforcement workflow: {:?}", e);
+            tracing::error!("Failed to generate billing documents: {:?}", e);
             Err(StatusCode::INTERNAL_SERVER_ERROR)
         }
     }
 }
 
-async fn renew_license_workflow_handler(
+async fn list_billing_documents_handler(
     State(state): State<AppState>,
-    Json(request): Json<LicenseRenewalWorkflowRequest>,
+    Path(tenant_id): Path<Uuid>,
+    Query(query): Query<PaginationQuery>,
+) -> Result<Json<ApiResponse<Vec<BillingDocument>>>, StatusCode> {
+    let limit = query.limit.unwrap_or(50);
+    let offset = query.offset.unwrap_or(0);
+
+    match state.license_service.list_billing_documents(tenant_id, limit, offset).await {
+        Ok(documents) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(documents),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to list billing documents: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn get_billing_document_download_handler(
+    State(state): State<AppState>,
+    Path((tenant_id, document_id)): Path<(Uuid, Uuid)>,
+) -> Result<Json<ApiResponse<BillingDocumentDownload>>, StatusCode> {
+    match state.license_service.get_billing_document_download(tenant_id, document_id).await {
+        Ok(download) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(download),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::BillingDocumentNotFound(_)) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to get billing document download: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Dunning handlers
+async fn list_dunning_cases_handler(
+    State(state): State<AppState>,
+    Query(query): Query<DunningQuery>,
+) -> Result<Json<ApiResponse<Vec<DunningCase>>>, StatusCode> {
+    let limit = query.limit.unwrap_or(50);
+    let offset = query.offset.unwrap_or(0);
+
+    match state.license_service.list_dunning_cases(query.status, limit, offset).await {
+        Ok(cases) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(cases),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to list dunning cases: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn get_dunning_case_handler(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<DunningCase>>, StatusCode> {
+    match state.license_service.get_dunning_case(id).await {
+        Ok(Some(case)) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(case),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to get dunning case: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn get_tenant_dunning_cases_handler(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<DunningCase>>>, StatusCode> {
+    match state.license_service.get_tenant_dunning_cases(tenant_id).await {
+        Ok(cases) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(cases),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to get tenant dunning cases: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn pause_dunning_case_handler(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<PauseDunningRequest>,
+) -> Result<Json<ApiResponse<DunningCase>>, StatusCode> {
+    dunning_case_response(state.license_service.pause_dunning_case(id, request).await, "pause")
+}
+
+async fn resume_dunning_case_handler(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<DunningCase>>, StatusCode> {
+    dunning_case_response(state.license_service.resume_dunning_case(id).await, "resume")
+}
+
+async fn forgive_dunning_case_handler(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<ForgiveDunningRequest>,
+) -> Result<Json<ApiResponse<DunningCase>>, StatusCode> {
+    dunning_case_response(state.license_service.forgive_dunning_case(id, request).await, "forgive")
+}
+
+fn dunning_case_response(result: Result<DunningCase>, action: &str) -> Result<Json<ApiResponse<DunningCase>>, StatusCode> {
+    match result {
+        Ok(case) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(case),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::DunningCaseNotFound(_)) => Err(StatusCode::NOT_FOUND),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::CONFLICT),
+        Err(e) => {
+            tracing::error!("Failed to {} dunning case: {:?}", action, e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Webhook handlers
+async fn stripe_webhook_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: String,
+) -> StatusCode {
+    let Some(signature) = headers.get("Stripe-Signature").and_then(|value| value.to_str().ok()) else {
+        return StatusCode::BAD_REQUEST;
+    };
+
+    webhook_status(state.license_service.handle_stripe_webhook(&body, signature).await, "Stripe")
+}
+
+async fn paypal_webhook_handler(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    body: String,
+) -> StatusCode {
+    let header = |name: &str| {
+        headers.get(name).and_then(|value| value.to_str().ok()).unwrap_or_default().to_string()
+    };
+    let paypal_headers = PayPalWebhookHeaders {
+        transmission_id: header("PAYPAL-TRANSMISSION-ID"),
+        transmission_time: header("PAYPAL-TRANSMISSION-TIME"),
+        transmission_sig: header("PAYPAL-TRANSMISSION-SIG"),
+        cert_url: header("PAYPAL-CERT-URL"),
+        auth_algo: header("PAYPAL-AUTH-ALGO"),
+    };
+    if paypal_headers.transmission_id.is_empty() || paypal_headers.transmission_sig.is_empty() {
+        return StatusCode::BAD_REQUEST;
+    }
+
+    webhook_status(state.license_service.handle_paypal_webhook(&paypal_headers, &body).await, "PayPal")
+}
+
+/// Providers redeliver events that do not get a 2xx, so an event that
+/// failed to apply returns a server error to have it retried
+fn webhook_status(result: Result<Option<BillingWebhookWorkflowResult>>, provider: &str) -> StatusCode {
+    match result {
+        Ok(Some(BillingWebhookWorkflowResult { status: WebhookEventStatus::Failed, .. })) => StatusCode::INTERNAL_SERVER_ERROR,
+        Ok(_) => StatusCode::OK,
+        Err(e @ (LicenseError::ValidationError(_) | LicenseError::SerializationError(_))) => {
+            tracing::warn!("Rejected {} webhook: {}", provider, e);
+            StatusCode::BAD_REQUEST
+        }
+        Err(e) => {
+            tracing::error!("Failed to handle {} webhook: {:?}", provider, e);
+            StatusCode::INTERNAL_SERVER_ERROR
+        }
+    }
+}
+
+async fn list_webhook_events_handler(
+    State(state): State<AppState>,
+    Query(query): Query<WebhookEventQuery>,
+) -> Result<Json<ApiResponse<Vec<WebhookEvent>>>, StatusCode> {
+    let limit = query.limit.unwrap_or(50);
+    let offset = query.offset.unwrap_or(0);
+
+    match state.license_service.list_webhook_events(query.provider.as_deref(), query.status, limit, offset).await {
+        Ok(events) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(events),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to list webhook events: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn get_webhook_event_handler(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<WebhookEvent>>, StatusCode> {
+    match state.license_service.get_webhook_event(id).await {
+        Ok(Some(event)) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(event),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Ok(None) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to get webhook event: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn replay_webhook_event_handler(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+) -> Result<Json<ApiResponse<BillingWebhookWorkflowResult>>, StatusCode> {
+    match state.license_service.replay_webhook_event(id).await {
+        Ok(result) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(result),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::WebhookEventNotFound(_)) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to replay webhook event: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Tax handlers
+async fn get_tax_profile_handler(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<TenantTaxProfile>>, StatusCode> {
+    match state.license_service.get_tax_profile(tenant_id).await {
+        Ok(profile) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(profile),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::TaxProfileNotFound(_)) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to get tax profile: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn upsert_tax_profile_handler(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<Uuid>,
+    Json(request): Json<UpsertTaxProfileRequest>,
+) -> Result<Json<ApiResponse<TenantTaxProfile>>, StatusCode> {
+    match state.license_service.upsert_tax_profile(tenant_id, request).await {
+        Ok(profile) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(profile),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(e) => {
+            tracing::error!("Failed to save tax profile: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn validate_tax_id_handler(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<TenantTaxProfile>>, StatusCode> {
+    match state.license_service.validate_tax_id(tenant_id).await {
+        Ok(profile) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(profile),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::TaxProfileNotFound(_)) => Err(StatusCode::NOT_FOUND),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(LicenseError::TaxError(e)) => {
+            tracing::warn!("Tax ID check failed: {}", e);
+            Err(StatusCode::BAD_GATEWAY)
+        }
+        Err(e) => {
+            tracing::error!("Failed to validate tax ID: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn list_tax_rates_handler(
+    State(state): State<AppState>,
+    Query(query): Query<TaxRateQuery>,
+) -> Result<Json<ApiResponse<Vec<TaxRate>>>, StatusCode> {
+    match state.license_service.list_tax_rates(query.country.as_deref()).await {
+        Ok(rates) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(rates),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to list tax rates: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn create_tax_rate_handler(
+    State(state): State<AppState>,
+    Json(request): Json<CreateTaxRateRequest>,
+) -> Result<Json<ApiResponse<TaxRate>>, StatusCode> {
+    match state.license_service.create_tax_rate(request).await {
+        Ok(rate) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(rate),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(e) => {
+            tracing::error!("Failed to create tax rate: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Pricing handlers
+async fn list_plan_prices_handler(
+    State(state): State<AppState>,
+    Query(query): Query<PlanPriceQuery>,
+) -> Result<Json<ApiResponse<Vec<PlanPrice>>>, StatusCode> {
+    match state.license_service.list_plan_prices(query.tenant_id, query.currency.as_deref()).await {
+        Ok(prices) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(prices),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(e) => {
+            tracing::error!("Failed to list plan prices: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn list_price_book_handler(
+    State(state): State<AppState>,
+    Query(query): Query<PriceBookQuery>,
+) -> Result<Json<ApiResponse<Vec<PriceBookEntry>>>, StatusCode> {
+    match state.license_service.list_price_book(query.currency.as_deref()).await {
+        Ok(entries) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(entries),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(e) => {
+            tracing::error!("Failed to list price book: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn upsert_price_book_entry_handler(
+    State(state): State<AppState>,
+    Json(request): Json<UpsertPriceBookEntryRequest>,
+) -> Result<Json<ApiResponse<PriceBookEntry>>, StatusCode> {
+    match state.license_service.upsert_price_book_entry(request).await {
+        Ok(entry) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(entry),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(e) => {
+            tracing::error!("Failed to save price book entry: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn get_tenant_currency_handler(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<String>>, StatusCode> {
+    match state.license_service.get_tenant_currency(tenant_id).await {
+        Ok(currency) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(currency),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to get tenant currency: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn set_tenant_currency_handler(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<Uuid>,
+    Json(request): Json<SetTenantCurrencyRequest>,
+) -> Result<Json<ApiResponse<TenantBillingCurrency>>, StatusCode> {
+    match state.license_service.set_tenant_currency(tenant_id, request).await {
+        Ok(currency) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(currency),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(e) => {
+            tracing::error!("Failed to set tenant currency: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn list_exchange_rates_handler(
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<Vec<ExchangeRate>>>, StatusCode> {
+    match state.license_service.list_exchange_rates().await {
+        Ok(rates) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(rates),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to list exchange rates: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn record_exchange_rate_handler(
+    State(state): State<AppState>,
+    Json(request): Json<RecordExchangeRateRequest>,
+) -> Result<Json<ApiResponse<ExchangeRate>>, StatusCode> {
+    match state.license_service.record_exchange_rate(request).await {
+        Ok(rate) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(rate),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(e) => {
+            tracing::error!("Failed to record exchange rate: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Promotion handlers
+async fn create_coupon_handler(
+    State(state): State<AppState>,
+    Json(request): Json<CreateCouponRequest>,
+) -> Result<Json<ApiResponse<Coupon>>, StatusCode> {
+    match state.license_service.create_coupon(request).await {
+        Ok(coupon) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(coupon),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(LicenseError::Database(sqlx::Error::Database(e))) if e.is_unique_violation() => Err(StatusCode::CONFLICT),
+        Err(e) => {
+            tracing::error!("Failed to create coupon: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn list_coupons_handler(
+    State(state): State<AppState>,
+    Query(query): Query<CouponQuery>,
+) -> Result<Json<ApiResponse<Vec<Coupon>>>, StatusCode> {
+    let active_only = query.active_only.unwrap_or(false);
+    let limit = query.limit.unwrap_or(50);
+    let offset = query.offset.unwrap_or(0);
+
+    match state.license_service.list_coupons(active_only, limit, offset).await {
+        Ok(coupons) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(coupons),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to list coupons: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn update_coupon_handler(
+    State(state): State<AppState>,
+    Path(code): Path<String>,
+    Json(request): Json<UpdateCouponRequest>,
+) -> Result<Json<ApiResponse<Coupon>>, StatusCode> {
+    match state.license_service.update_coupon(&code, request).await {
+        Ok(coupon) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(coupon),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::CouponNotFound(_)) => Err(StatusCode::NOT_FOUND),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(e) => {
+            tracing::error!("Failed to update coupon: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn get_coupon_redemptions_handler(
+    State(state): State<AppState>,
+    Path(code): Path<String>,
+    Query(pagination): Query<PaginationQuery>,
+) -> Result<Json<ApiResponse<Vec<CouponRedemption>>>, StatusCode> {
+    let limit = pagination.limit.unwrap_or(50);
+    let offset = pagination.offset.unwrap_or(0);
+
+    match state.license_service.get_coupon_redemptions(&code, limit, offset).await {
+        Ok(redemptions) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(redemptions),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::CouponNotFound(_)) => Err(StatusCode::NOT_FOUND),
+        Err(e) => {
+            tracing::error!("Failed to get coupon redemptions: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn validate_promotion_handler(
+    State(state): State<AppState>,
+    Json(request): Json<ValidatePromotionRequest>,
+) -> Result<Json<ApiResponse<PromotionQuote>>, StatusCode> {
+    match state.license_service.validate_promotion(request).await {
+        Ok(quote) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(quote),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::CouponNotFound(_)) => Err(StatusCode::NOT_FOUND),
+        Err(LicenseError::InvalidCoupon(_)) | Err(LicenseError::ValidationError(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(e) => {
+            tracing::error!("Failed to validate promotion: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn grant_credit_handler(
+    State(state): State<AppState>,
+    Json(request): Json<GrantCreditRequest>,
+) -> Result<Json<ApiResponse<CreditEntry>>, StatusCode> {
+    match state.license_service.grant_credit(request).await {
+        Ok(entry) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(entry),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(e) => {
+            tracing::error!("Failed to grant credit: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn get_tenant_credits_handler(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<Uuid>,
+    Query(pagination): Query<PaginationQuery>,
+) -> Result<Json<ApiResponse<TenantCredits>>, StatusCode> {
+    let limit = pagination.limit.unwrap_or(50);
+    let offset = pagination.offset.unwrap_or(0);
+
+    match state.license_service.get_tenant_credits(tenant_id, limit, offset).await {
+        Ok(credits) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(credits),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to get tenant credits: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Compliance handlers
+async fn get_compliance_logs_handler(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<Uuid>,
+    Query(query): Query<DateRangeQuery>,
+) -> Result<Json<ApiResponse<Vec<ComplianceLog>>>, StatusCode> {
+    let start_date = query.start_date.unwrap_or_else(|| Utc::now() - chrono::Duration::days(30));
+    let end_date = query.end_date.unwrap_or_else(Utc::now);
+    
+    match state.license_service.get_compliance_logs(tenant_id, start_date, end_date).await {
+        Ok(logs) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(logs),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to get compliance logs: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn generate_compliance_report_handler(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<Uuid>,
+    Query(query): Query<DateRangeQuery>,
+) -> Result<Json<ApiResponse<ComplianceReport>>, StatusCode> {
+    let start_date = query.start_date.unwrap_or_else(|| Utc::now() - chrono::Duration::days(30));
+    let end_date = query.end_date.unwrap_or_else(Utc::now);
+    
+    match state.license_service.generate_compliance_report(tenant_id, start_date, end_date).await {
+        Ok(report) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(report),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to generate compliance report: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn resolve_compliance_issue_handler(
+    State(state): State<AppState>,
+    Path(id): Path<Uuid>,
+    Json(request): Json<ResolveComplianceIssueRequest>,
+) -> Result<Json<ApiResponse<()>>, StatusCode> {
+    match state.license_service.resolve_compliance_issue(id, request.resolved_by, request.resolution_notes).await {
+        Ok(_) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(()),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to resolve compliance issue: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+// Workflow handlers
+async fn provision_license_workflow_handler(
+    State(state): State<AppState>,
+    Json(request): Json<LicenseProvisioningWorkflowRequest>,
+) -> Result<Json<ApiResponse<WorkflowResponse>>, StatusCode> {
+    match state.license_service.initiate_license_provisioning(request).await {
+        Ok(workflow_id) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(WorkflowResponse {
+                workflow_id,
+                status: "started".to_string(),
+                message: "License provisioning workflow initiated".to_string(),
+            }),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::CouponNotFound(_)) => Err(StatusCode::NOT_FOUND),
+        Err(LicenseError::InvalidCoupon(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(e) => {
+            tracing::error!("Failed to start license provisioning workflow: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn enforce_quota_workflow_handler(
+    State(state): State<AppState>,
+    Json(request): Json<QuotaEnforcementWorkflowRequest>,
+) -> Result<Json<ApiResponse<WorkflowResponse>>, StatusCode> {
+    match state.license_service.initiate_quota_enforcement(request).await {
+        Ok(workflow_id) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(WorkflowResponse {
+                workflow_id,
+                status: "started".to_string(),
+                message: "Quota enforcement workflow initiated".to_string(),
+            }),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to start quota enforcement workflow: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn renew_license_workflow_handler(
+    State(state): State<AppState>,
+    Json(request): Json<LicenseRenewalWorkflowRequest>,
 ) -> Result<Json<ApiResponse<WorkflowResponse>>, StatusCode> {
     match state.license_service.initiate_license_renewal(request).await {
         Ok(workflow_id) => Ok(Json(ApiResponse {
@@ -496,6 +1591,76 @@ async fn renew_license_workflow_handler(
     }
 }
 
+async fn change_subscription_workflow_handler(
+    State(state): State<AppState>,
+    Json(request): Json<ChangeSubscriptionWorkflowRequest>,
+) -> Result<Json<ApiResponse<WorkflowResponse>>, StatusCode> {
+    match state.license_service.initiate_subscription_change(request).await {
+        Ok(workflow_id) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(WorkflowResponse {
+                workflow_id,
+                status: "started".to_string(),
+                message: "Subscription change workflow initiated".to_string(),
+            }),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::LicenseNotFound(_)) => Err(StatusCode::NOT_FOUND),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::BAD_REQUEST),
+        Err(e) => {
+            tracing::error!("Failed to start subscription change workflow: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn dunning_workflow_handler(
+    State(state): State<AppState>,
+    Json(request): Json<DunningWorkflowRequest>,
+) -> Result<Json<ApiResponse<WorkflowResponse>>, StatusCode> {
+    match state.license_service.initiate_dunning(request).await {
+        Ok(workflow_id) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(WorkflowResponse {
+                workflow_id,
+                status: "started".to_string(),
+                message: "Dunning workflow initiated".to_string(),
+            }),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(LicenseError::DunningCaseNotFound(_)) => Err(StatusCode::NOT_FOUND),
+        Err(LicenseError::ValidationError(_)) => Err(StatusCode::CONFLICT),
+        Err(e) => {
+            tracing::error!("Failed to start dunning workflow: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
+async fn reconcile_seats_workflow_handler(
+    State(state): State<AppState>,
+    Json(request): Json<SeatReconciliationWorkflowRequest>,
+) -> Result<Json<ApiResponse<WorkflowResponse>>, StatusCode> {
+    match state.license_service.initiate_seat_reconciliation(request).await {
+        Ok(workflow_id) => Ok(Json(ApiResponse {
+            success: true,
+            data: Some(WorkflowResponse {
+                workflow_id,
+                status: "started".to_string(),
+                message: "Seat reconciliation workflow initiated".to_string(),
+            }),
+            error: None,
+            timestamp: Utc::now(),
+        })),
+        Err(e) => {
+            tracing::error!("Failed to start seat reconciliation workflow: {:?}", e);
+            Err(StatusCode::INTERNAL_SERVER_ERROR)
+        }
+    }
+}
+
 // Analytics handler
 async fn get_license_analytics_handler(
     State(state): State<AppState>,
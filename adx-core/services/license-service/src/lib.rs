@@ -5,6 +5,17 @@ pub mod workflows;
 pub mod activities;
 pub mod handlers;
 pub mod billing;
+pub mod tenants;
+pub mod branding;
+pub mod files;
+pub mod grpc;
+pub mod modules;
+pub mod documents;
+pub mod promotions;
+pub mod tax;
+pub mod pricing;
+pub mod notifications;
+pub mod quota_cache;
 pub mod config;
 pub mod error;
 
@@ -11,27 +11,35 @@ use tower_http::{
     timeout::TimeoutLayer,
 };
 use tracing::{info, warn};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use adx_shared::telemetry::{init_tracing, trace_http_requests};
 
 use license_service::{
     billing::BillingService,
+    branding::BrandingClient,
     config::LicenseConfig,
+    files::FileServiceClient,
+    grpc::QuotaGrpcService,
     handlers::{create_router, AppState},
-    repositories::{LicenseRepository, QuotaRepository, BillingRepository, ComplianceRepository},
+    modules::ModuleServiceClient,
+    notifications::EmailClient,
+    pricing::ExchangeRateClient,
+    quota_cache::QuotaCache,
+    repositories::{LicenseRepository, QuotaRepository, BillingRepository, BillingDocumentRepository, ComplianceRepository, SeatRepository, DunningRepository, WebhookEventRepository, PromotionRepository, TaxRepository, PricingRepository},
     services::LicenseService,
+    tax::TaxService,
+    tenants::TenantServiceClient,
+    workflows::SeatReconciliationWorkflowRequest,
     LicenseError, Result,
 };
 
+/// Billing records the worker generates documents for per run
+const BILLING_DOCUMENT_BATCH_SIZE: i64 = 100;
+
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "license_service=debug,tower_http=debug".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    // Initialize tracing, exporting spans when OTEL_EXPORTER_OTLP_ENDPOINT is set
+    let _telemetry = init_tracing("license-service", "license_service=debug,tower_http=debug")
+        .map_err(|e| LicenseError::Internal(e.to_string()))?;
 
     // Parse command line arguments
     let matches = Command::new("license-service")
@@ -94,6 +102,13 @@ async fn run_server(config: LicenseConfig) -> Result<()> {
     let quota_repo = QuotaRepository::new(database_pool.clone());
     let billing_repo = BillingRepository::new(database_pool.clone());
     let compliance_repo = ComplianceRepository::new(database_pool.clone());
+    let seat_repo = SeatRepository::new(database_pool.clone());
+    let dunning_repo = DunningRepository::new(database_pool.clone());
+    let webhook_repo = WebhookEventRepository::new(database_pool.clone());
+    let document_repo = BillingDocumentRepository::new(database_pool.clone());
+    let promotion_repo = PromotionRepository::new(database_pool.clone());
+    let tax_repo = TaxRepository::new(database_pool.clone());
+    let pricing_repo = PricingRepository::new(database_pool.clone());
 
     // Initialize billing service
     let billing_service = BillingService::new(
@@ -101,6 +116,7 @@ async fn run_server(config: LicenseConfig) -> Result<()> {
         Some(config.paypal.clone()),
         config.billing.clone(),
     );
+    let tax_service = TaxService::new(&config.tax, &config.stripe, &config.billing, tax_repo.clone())?;
 
     // Initialize license service
     let license_service = LicenseService::new(
@@ -108,9 +124,43 @@ async fn run_server(config: LicenseConfig) -> Result<()> {
         quota_repo,
         billing_repo,
         compliance_repo,
+        seat_repo,
+        dunning_repo,
+        webhook_repo,
+        document_repo,
+        promotion_repo,
+        tax_repo,
+        pricing_repo,
         billing_service,
+        tax_service,
+        ExchangeRateClient::new(&config.currencies),
+        TenantServiceClient::new(&config.tenant_service_url),
+        EmailClient::new(config.notifications.clone()),
+        config.dunning.clone(),
+        config.currencies.clone(),
+        config.grace.clone(),
+        QuotaCache::new(&config.redis_url, config.quotas.limit_cache_seconds)?,
+        BrandingClient::new(&config.documents),
+        FileServiceClient::new(&config.documents.file_service_url),
+        ModuleServiceClient::new(&config.module_service_url),
     );
 
+    // Quota checks and seat changes for the other services over gRPC
+    let grpc_addr = format!("0.0.0.0:{}", config.grpc_port)
+        .parse()
+        .map_err(|e| LicenseError::ConfigError(format!("Invalid gRPC port {}: {}", config.grpc_port, e)))?;
+    let grpc_service = QuotaGrpcService::new(license_service.clone()).into_server();
+    tokio::spawn(async move {
+        info!("License service gRPC server listening on {}", grpc_addr);
+        if let Err(e) = tonic::transport::Server::builder()
+            .add_service(grpc_service)
+            .serve_with_shutdown(grpc_addr, shutdown_signal())
+            .await
+        {
+            tracing::error!("gRPC server error: {}", e);
+        }
+    });
+
     // Create application state
     let app_state = AppState {
         license_service,
@@ -120,6 +170,7 @@ async fn run_server(config: LicenseConfig) -> Result<()> {
     let app = create_router(app_state)
         .layer(
             ServiceBuilder::new()
+                .layer(axum::middleware::from_fn(trace_http_requests))
                 .layer(TraceLayer::new_for_http())
                 .layer(CorsLayer::permissive())
                 .layer(TimeoutLayer::from_secs(30))
@@ -154,6 +205,13 @@ async fn run_worker(config: LicenseConfig) -> Result<()> {
     let quota_repo = QuotaRepository::new(database_pool.clone());
     let billing_repo = BillingRepository::new(database_pool.clone());
     let compliance_repo = ComplianceRepository::new(database_pool.clone());
+    let seat_repo = SeatRepository::new(database_pool.clone());
+    let dunning_repo = DunningRepository::new(database_pool.clone());
+    let webhook_repo = WebhookEventRepository::new(database_pool.clone());
+    let document_repo = BillingDocumentRepository::new(database_pool.clone());
+    let promotion_repo = PromotionRepository::new(database_pool.clone());
+    let tax_repo = TaxRepository::new(database_pool.clone());
+    let pricing_repo = PricingRepository::new(database_pool.clone());
 
     // Initialize billing service
     let billing_service = BillingService::new(
@@ -161,6 +219,7 @@ async fn run_worker(config: LicenseConfig) -> Result<()> {
         Some(config.paypal.clone()),
         config.billing.clone(),
     );
+    let tax_service = TaxService::new(&config.tax, &config.stripe, &config.billing, tax_repo.clone())?;
 
     // Initialize license service
     let license_service = LicenseService::new(
@@ -168,11 +227,110 @@ async fn run_worker(config: LicenseConfig) -> Result<()> {
         quota_repo,
         billing_repo,
         compliance_repo,
+        seat_repo,
+        dunning_repo,
+        webhook_repo,
+        document_repo,
+        promotion_repo,
+        tax_repo,
+        pricing_repo,
         billing_service,
+        tax_service,
+        ExchangeRateClient::new(&config.currencies),
+        TenantServiceClient::new(&config.tenant_service_url),
+        EmailClient::new(config.notifications.clone()),
+        config.dunning.clone(),
+        config.currencies.clone(),
+        config.grace.clone(),
+        QuotaCache::new(&config.redis_url, config.quotas.limit_cache_seconds)?,
+        BrandingClient::new(&config.documents),
+        FileServiceClient::new(&config.documents.file_service_url),
+        ModuleServiceClient::new(&config.module_service_url),
     );
 
     info!("License service worker initialized");
 
+    // Seat reconciliation runs in process until workflows execute on Temporal
+    if config.seats.reconciliation_enabled {
+        let license_service = license_service.clone();
+        let seats = config.seats.clone();
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(until_hour_utc(seats.reconciliation_hour_utc)).await;
+
+                let request = SeatReconciliationWorkflowRequest {
+                    tenant_ids: None,
+                    adjust_billing: seats.adjust_billing,
+                };
+                match license_service.reconcile_seats(request).await {
+                    Ok(result) => info!(
+                        "Seat reconciliation checked {} tenants: {} drifted, {} failed",
+                        result.tenants_checked,
+                        result.tenants_drifted,
+                        result.failed_tenants.len()
+                    ),
+                    Err(e) => warn!("Seat reconciliation failed: {}", e),
+                }
+            }
+        });
+        info!("Seat reconciliation scheduled daily at {:02}:00 UTC", config.seats.reconciliation_hour_utc);
+    }
+
+    // Dunning retries run in process until workflows execute on Temporal
+    if config.dunning.enabled {
+        let license_service = license_service.clone();
+        let interval = std::time::Duration::from_secs(config.dunning.check_interval_minutes.max(1) * 60);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+
+                match license_service.process_due_dunning_cases().await {
+                    Ok(0) => {}
+                    Ok(processed) => info!("Processed {} due dunning cases", processed),
+                    Err(e) => warn!("Dunning run failed: {}", e),
+                }
+            }
+        });
+        info!("Dunning retries checked every {} minutes", config.dunning.check_interval_minutes);
+    }
+
+    // Invoices and receipts the API couldn't generate straight away
+    if config.documents.enabled {
+        let license_service = license_service.clone();
+        let interval = std::time::Duration::from_secs(config.documents.generation_interval_minutes.max(1) * 60);
+        tokio::spawn(async move {
+            loop {
+                tokio::time::sleep(interval).await;
+
+                match license_service.generate_missing_billing_documents(BILLING_DOCUMENT_BATCH_SIZE).await {
+                    Ok(0) => {}
+                    Ok(completed) => info!("Generated billing documents for {} records", completed),
+                    Err(e) => warn!("Billing document generation failed: {}", e),
+                }
+            }
+        });
+        info!("Billing documents generated every {} minutes", config.documents.generation_interval_minutes);
+    }
+
+    // Exchange rates prices and invoices are converted at, refreshed on
+    // start so a new currency can be priced straight away
+    if config.currencies.refresh_enabled {
+        let license_service = license_service.clone();
+        let interval = std::time::Duration::from_secs(config.currencies.refresh_interval_minutes.max(1) * 60);
+        tokio::spawn(async move {
+            loop {
+                match license_service.refresh_exchange_rates().await {
+                    Ok(0) => {}
+                    Ok(recorded) => info!("Recorded {} exchange rates", recorded),
+                    Err(e) => warn!("Exchange rate refresh failed: {}", e),
+                }
+
+                tokio::time::sleep(interval).await;
+            }
+        });
+        info!("Exchange rates refreshed every {} minutes", config.currencies.refresh_interval_minutes);
+    }
+
     // TODO: Initialize Temporal worker
     // This would typically involve:
     // 1. Creating a Temporal client
@@ -195,6 +353,19 @@ async fn run_worker(config: LicenseConfig) -> Result<()> {
     Ok(())
 }
 
+/// Time left until the next time the clock reaches `hour` UTC
+fn until_hour_utc(hour: u32) -> std::time::Duration {
+    use chrono::{TimeZone, Utc};
+
+    let now = Utc::now();
+    let today = now.date_naive().and_hms_opt(hour.min(23), 0, 0).unwrap_or_else(|| now.naive_utc());
+    let mut next = Utc.from_utc_datetime(&today);
+    if next <= now {
+        next = next + chrono::Duration::days(1);
+    }
+    (next - now).to_std().unwrap_or_default()
+}
+
 async fn shutdown_signal() {
     let ctrl_c = async {
         signal::ctrl_c()
@@ -1,10 +1,10 @@
-use chrono::{DateTime, Utc};
+use chrono::{DateTime, Datelike, Duration, TimeZone, Timelike, Utc};
 use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "subscription_tier", rename_all = "lowercase")]
 pub enum SubscriptionTier {
     Free,
@@ -23,7 +23,7 @@ pub enum LicenseStatus {
     Pending,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
 #[sqlx(type_name = "billing_cycle", rename_all = "lowercase")]
 pub enum BillingCycle {
     Monthly,
@@ -42,6 +42,67 @@ pub enum PaymentStatus {
     Cancelled,
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "dunning_status", rename_all = "lowercase")]
+pub enum DunningStatus {
+    Active,
+    Paused,
+    Recovered,
+    Forgiven,
+    Suspended,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "webhook_event_status", rename_all = "lowercase")]
+pub enum WebhookEventStatus {
+    Received,
+    Processed,
+    Ignored,
+    Failed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "billing_document_type", rename_all = "lowercase")]
+pub enum BillingDocumentType {
+    Invoice,
+    Receipt,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "coupon_discount_type", rename_all = "lowercase")]
+pub enum CouponDiscountType {
+    Percentage,
+    Fixed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "credit_entry_type", rename_all = "lowercase")]
+pub enum CreditEntryType {
+    Grant,
+    Applied,
+    Reversal,
+}
+
+/// Outcome of the last check of a tenant's tax ID against its registry
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "tax_id_status", rename_all = "lowercase")]
+pub enum TaxIdStatus {
+    Unverified,
+    Valid,
+    Invalid,
+}
+
+/// When a quota's usage goes back to zero. `Never` quotas are allocations
+/// that usage is released from instead.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "quota_reset_period", rename_all = "lowercase")]
+pub enum QuotaResetPeriod {
+    Never,
+    Hourly,
+    Daily,
+    Monthly,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct License {
     pub id: Uuid,
@@ -69,6 +130,9 @@ pub struct License {
     pub stripe_customer_id: Option<String>,
     pub paypal_subscription_id: Option<String>,
     
+    /// When the license last stopped being active; the grace period runs from here
+    pub lapsed_at: Option<DateTime<Utc>>,
+    
     // Metadata
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
@@ -82,6 +146,7 @@ pub struct QuotaDefinition {
     pub description: Option<String>,
     pub unit: String,
     pub category: String,
+    pub reset_period: QuotaResetPeriod,
     
     // Default limits per tier
     pub free_limit: i64,
@@ -169,6 +234,31 @@ pub struct BillingHistory {
     pub updated_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ModuleUsageCharge {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub module_id: String,
+    pub instance_id: Uuid,
+    pub developer_id: String,
+    
+    // Usage period and metric
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub metric: String,
+    pub quantity: Decimal,
+    pub unit_price: Decimal,
+    
+    // Charge and revenue share
+    pub amount: Decimal,
+    pub developer_amount: Decimal,
+    pub platform_amount: Decimal,
+    pub currency: String,
+    
+    pub invoice_number: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct ComplianceLog {
     pub id: Uuid,
@@ -197,6 +287,264 @@ pub struct ComplianceLog {
     pub created_at: DateTime<Utc>,
 }
 
+/// The quota whose seats are assigned to users
+pub const SEAT_QUOTA: &str = "users_per_tenant";
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SeatAssignment {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub assigned_by: Option<Uuid>,
+    pub assigned_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SeatReconciliation {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    
+    // Seats counted from assignments against what the quota and Stripe held
+    pub assigned_seats: i64,
+    pub recorded_usage: i64,
+    pub billed_seats: Option<i64>,
+    
+    // What the run corrected
+    pub usage_corrected: bool,
+    pub billing_corrected: bool,
+    pub error: Option<String>,
+    
+    pub reconciled_at: DateTime<Utc>,
+}
+
+/// A failed invoice being recovered by retrying the charge and
+/// progressively restricting the tenant
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DunningCase {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub license_id: Uuid,
+    pub stripe_invoice_id: String,
+    pub amount_due: Decimal,
+    pub currency: String,
+    pub status: DunningStatus,
+    
+    // Retry schedule and how far the tenant has been restricted
+    pub attempt_count: i32,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub last_error: Option<String>,
+    pub restricted_features: Vec<String>,
+    
+    // Operator actions
+    pub paused_by: Option<Uuid>,
+    pub paused_at: Option<DateTime<Utc>>,
+    pub resolved_by: Option<Uuid>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub resolution_notes: Option<String>,
+    
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A webhook delivery from Stripe or PayPal, recorded before it is applied
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WebhookEvent {
+    pub id: Uuid,
+    pub provider: String, // 'stripe', 'paypal'
+    pub event_id: String,
+    pub event_type: String,
+    pub payload: serde_json::Value,
+    pub status: WebhookEventStatus,
+    pub attempts: i32,
+    pub error: Option<String>,
+    pub received_at: DateTime<Utc>,
+    pub processed_at: Option<DateTime<Utc>>,
+}
+
+/// An invoice or receipt PDF of a billing record, stored in file-service
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct BillingDocument {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub billing_history_id: Uuid,
+    pub document_type: BillingDocumentType,
+    pub document_number: String,
+    pub file_id: Uuid,
+    pub filename: String,
+    pub file_size: i64,
+    pub amount: Decimal,
+    pub currency: String,
+    pub issued_at: DateTime<Utc>,
+}
+
+/// A promotional code tenants can redeem when subscribing
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Coupon {
+    pub id: Uuid,
+    pub code: String,
+    pub description: Option<String>,
+    pub discount_type: CouponDiscountType,
+    /// Percent off for percentage coupons, amount off for fixed ones
+    pub discount_value: Decimal,
+    pub currency: Option<String>,
+    /// Tiers the coupon can be used with; any tier when empty
+    pub eligible_tiers: Vec<String>,
+    
+    // Redemption limits
+    pub max_redemptions: Option<i32>,
+    pub max_redemptions_per_tenant: i32,
+    pub redemption_count: i32,
+    
+    pub valid_from: DateTime<Utc>,
+    pub valid_until: Option<DateTime<Utc>>,
+    pub active: bool,
+    
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CouponRedemption {
+    pub id: Uuid,
+    pub coupon_id: Uuid,
+    pub tenant_id: Uuid,
+    pub license_id: Option<Uuid>,
+    pub original_amount: Decimal,
+    pub discount_amount: Decimal,
+    pub currency: String,
+    pub redeemed_at: DateTime<Utc>,
+}
+
+/// An entry in a tenant's credit ledger; grants are positive and credit
+/// used on charges negative
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CreditEntry {
+    pub id: Uuid,
+    pub tenant_id: Uuid,
+    pub entry_type: CreditEntryType,
+    pub amount: Decimal,
+    pub currency: String,
+    pub reason: String,
+    pub reference: Option<String>,
+    pub created_by: Option<Uuid>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Where a tenant is billed, and its tax registration
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TenantTaxProfile {
+    pub tenant_id: Uuid,
+    pub legal_name: Option<String>,
+    /// ISO 3166-1 alpha-2
+    pub country: String,
+    /// State or province, e.g. "CA"
+    pub region: Option<String>,
+    pub postal_code: Option<String>,
+    
+    // Tax registration
+    pub tax_id: Option<String>,
+    pub tax_id_status: TaxIdStatus,
+    pub tax_id_checked_at: Option<DateTime<Utc>>,
+    pub tax_exempt: bool,
+    
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A rate of the manual tax tables
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TaxRate {
+    pub id: Uuid,
+    pub country: String,
+    /// The whole country when unset
+    pub region: Option<String>,
+    pub tax_type: String, // 'vat', 'gst', 'sales_tax'
+    pub name: String,
+    /// Percent, e.g. 19.0
+    pub rate: Decimal,
+    pub active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Tax charged on an invoice line in one jurisdiction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaxBreakdown {
+    pub tax_type: String,
+    pub jurisdiction: String,
+    /// Percent
+    pub rate: Decimal,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct InvoiceTaxLine {
+    pub id: Uuid,
+    pub invoice_number: String,
+    pub tenant_id: Uuid,
+    pub line_index: i32,
+    pub description: String,
+    pub taxable_amount: Decimal,
+    pub tax_type: String,
+    pub jurisdiction: String,
+    pub rate: Decimal,
+    pub tax_amount: Decimal,
+    pub currency: String,
+    pub provider: String,
+    pub reverse_charge: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// A plan's price in one currency
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct PriceBookEntry {
+    pub id: Uuid,
+    pub currency: String,
+    pub subscription_tier: SubscriptionTier,
+    pub billing_cycle: BillingCycle,
+    pub price: Decimal,
+    pub setup_fee: Decimal,
+    pub stripe_price_id: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One unit of the base currency is worth `rate` units of the quote currency
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ExchangeRate {
+    pub id: Uuid,
+    pub base_currency: String,
+    pub quote_currency: String,
+    pub rate: Decimal,
+    pub source: String,
+    pub effective_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct TenantBillingCurrency {
+    pub tenant_id: Uuid,
+    pub currency: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// The exchange rate an invoice was generated at, kept with the invoice
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct InvoiceExchangeRate {
+    pub invoice_number: String,
+    pub tenant_id: Uuid,
+    pub base_currency: String,
+    pub invoice_currency: String,
+    pub rate: Decimal,
+    pub source: String,
+    pub rate_effective_at: DateTime<Utc>,
+    /// The invoice total in the base currency
+    pub base_amount: Decimal,
+    pub created_at: DateTime<Utc>,
+}
+
 // Request/Response DTOs
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateLicenseRequest {
@@ -213,6 +561,7 @@ pub struct CreateLicenseRequest {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct UpdateLicenseRequest {
     pub subscription_tier: Option<SubscriptionTier>,
+    pub billing_cycle: Option<BillingCycle>,
     pub status: Option<LicenseStatus>,
     pub base_price: Option<Decimal>,
     pub expires_at: Option<DateTime<Utc>>,
@@ -232,6 +581,224 @@ pub struct QuotaUsageRequest {
     pub metadata: Option<serde_json::Value>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateCouponRequest {
+    pub code: String,
+    pub description: Option<String>,
+    pub discount_type: CouponDiscountType,
+    pub discount_value: Decimal,
+    /// Required for fixed coupons
+    pub currency: Option<String>,
+    pub eligible_tiers: Option<Vec<SubscriptionTier>>,
+    pub max_redemptions: Option<i32>,
+    pub max_redemptions_per_tenant: Option<i32>,
+    pub valid_from: Option<DateTime<Utc>>,
+    pub valid_until: Option<DateTime<Utc>>,
+    pub created_by: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateCouponRequest {
+    pub description: Option<String>,
+    pub max_redemptions: Option<i32>,
+    pub valid_until: Option<DateTime<Utc>>,
+    pub active: Option<bool>,
+}
+
+/// What a tenant would pay for a charge with a coupon and its credit
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValidatePromotionRequest {
+    pub tenant_id: Uuid,
+    pub coupon_code: Option<String>,
+    pub subscription_tier: SubscriptionTier,
+    pub amount: Decimal,
+    pub currency: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PromotionQuote {
+    pub coupon_code: Option<String>,
+    pub original_amount: Decimal,
+    pub discount_amount: Decimal,
+    /// Credit that would be used, from the balance at the time of the quote
+    pub credits_applied: Decimal,
+    pub amount_due: Decimal,
+    pub currency: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GrantCreditRequest {
+    pub tenant_id: Uuid,
+    pub amount: Decimal,
+    pub currency: String,
+    pub reason: String,
+    pub granted_by: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreditBalance {
+    pub currency: String,
+    pub balance: Decimal,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TenantCredits {
+    pub tenant_id: Uuid,
+    pub balances: Vec<CreditBalance>,
+    pub entries: Vec<CreditEntry>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpsertTaxProfileRequest {
+    pub legal_name: Option<String>,
+    pub country: String,
+    pub region: Option<String>,
+    pub postal_code: Option<String>,
+    pub tax_id: Option<String>,
+    pub tax_exempt: Option<bool>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateTaxRateRequest {
+    pub country: String,
+    pub region: Option<String>,
+    pub tax_type: String,
+    pub name: String,
+    pub rate: Decimal,
+}
+
+/// A plan's price as presented to a tenant
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PlanPrice {
+    pub subscription_tier: SubscriptionTier,
+    pub billing_cycle: BillingCycle,
+    pub currency: String,
+    pub price: Decimal,
+    pub setup_fee: Decimal,
+    pub stripe_price_id: Option<String>,
+    /// Rate the price was converted from the default currency's price book
+    /// at, when the currency has no price of its own
+    pub exchange_rate: Option<ExchangeRate>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpsertPriceBookEntryRequest {
+    pub currency: String,
+    pub subscription_tier: SubscriptionTier,
+    pub billing_cycle: BillingCycle,
+    pub price: Decimal,
+    pub setup_fee: Option<Decimal>,
+    pub stripe_price_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetTenantCurrencyRequest {
+    pub currency: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordExchangeRateRequest {
+    pub base_currency: String,
+    pub quote_currency: String,
+    pub rate: Decimal,
+}
+
+/// Everything a tenant may use: its plan's features, quota limits and
+/// module licenses, in one answer shared by every service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Entitlements {
+    pub tenant_id: Uuid,
+    pub subscription_tier: Option<SubscriptionTier>,
+    pub license_status: Option<LicenseStatus>,
+    /// Without an active license the tenant is entitled to no features
+    pub active: bool,
+    /// Plan features, less any restricted while payment is being recovered
+    pub features: Vec<String>,
+    pub restricted_features: Vec<String>,
+    pub quotas: Vec<QuotaEntitlement>,
+    pub modules: Vec<ModuleEntitlement>,
+    /// What the gateway lets the tenant do
+    pub access_mode: AccessMode,
+    /// End of the read-only grace period of a lapsed license
+    pub grace_ends_at: Option<DateTime<Utc>>,
+    pub generated_at: DateTime<Utc>,
+}
+
+/// How much of the platform a tenant can use while its license lapses
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessMode {
+    Full,
+    /// The license lapsed and the tenant is in its grace period: data can
+    /// be read but not changed
+    ReadOnly,
+    /// The grace period is over, or there is none
+    Suspended,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaEntitlement {
+    pub quota_name: String,
+    /// -1 means unlimited
+    pub quota_limit: i64,
+    pub current_usage: i64,
+    /// -1 means unlimited
+    pub remaining: i64,
+    pub unit: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleEntitlement {
+    pub module_id: String,
+    pub version: String,
+    /// Installed and switched on
+    pub active: bool,
+}
+
+/// Where a billing document can be downloaded from, for a limited time
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BillingDocumentDownload {
+    pub document_id: Uuid,
+    pub filename: String,
+    pub download_url: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateQuotaDefinitionRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub unit: String,
+    pub category: String,
+    pub reset_period: QuotaResetPeriod,
+    pub free_limit: i64,
+    pub professional_limit: i64,
+    pub enterprise_limit: i64,
+    pub enforce_hard_limit: Option<bool>,
+    pub warning_threshold_percent: Option<i32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateQuotaDefinitionRequest {
+    pub description: Option<String>,
+    pub unit: Option<String>,
+    pub category: Option<String>,
+    pub reset_period: Option<QuotaResetPeriod>,
+    pub free_limit: Option<i64>,
+    pub professional_limit: Option<i64>,
+    pub enterprise_limit: Option<i64>,
+    pub enforce_hard_limit: Option<bool>,
+    pub warning_threshold_percent: Option<i32>,
+}
+
+/// Count usage against a quota, or give it back for allocation quotas
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConsumeQuotaRequest {
+    pub tenant_id: Uuid,
+    pub quota_name: String,
+    pub amount: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct QuotaCheckResult {
     pub allowed: bool,
@@ -242,6 +809,92 @@ pub struct QuotaCheckResult {
     pub quota_name: String,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssignSeatRequest {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+    pub assigned_by: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UnassignSeatRequest {
+    pub tenant_id: Uuid,
+    pub user_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SeatSummary {
+    pub tenant_id: Uuid,
+    pub seat_limit: i64, // -1 means unlimited
+    pub assigned_seats: i64,
+    pub available_seats: i64, // -1 means unlimited
+    pub assignments: Vec<SeatAssignment>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OpenDunningCaseRequest {
+    pub tenant_id: Uuid,
+    pub license_id: Uuid,
+    pub stripe_invoice_id: String,
+    pub amount_due: Decimal,
+    pub currency: String,
+    pub next_attempt_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PauseDunningRequest {
+    pub paused_by: Option<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ForgiveDunningRequest {
+    pub forgiven_by: Option<Uuid>,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SubscriptionChangeRequest {
+    pub tenant_id: Uuid,
+    pub new_tier: SubscriptionTier,
+    /// Keeps the current billing cycle when not set
+    pub new_billing_cycle: Option<BillingCycle>,
+}
+
+/// What a plan change would cost, shown before it is confirmed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProrationPreview {
+    /// Identifies the change, so a retried capture credits it once
+    pub id: Uuid,
+    pub license_id: Uuid,
+    pub tenant_id: Uuid,
+    pub current_tier: SubscriptionTier,
+    pub new_tier: SubscriptionTier,
+    pub current_billing_cycle: BillingCycle,
+    pub new_billing_cycle: BillingCycle,
+    pub current_price: Decimal,
+    pub new_price: Decimal,
+    
+    // Proration over what is left of the current period
+    pub days_remaining: i64,
+    pub unused_credit: Decimal,
+    pub new_charge: Decimal,
+    pub amount_due: Decimal, // Negative when the change leaves a credit
+    pub currency: String,
+    pub new_period_end: Option<DateTime<Utc>>,
+    
+    pub quota_changes: Vec<QuotaLimitChange>,
+    /// Why the change cannot go ahead, e.g. more seats in use than the new plan has
+    pub blocked_reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaLimitChange {
+    pub quota_name: String,
+    pub current_limit: i64,
+    pub new_limit: i64,
+    pub current_usage: i64,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct BillingInvoice {
     pub invoice_number: String,
@@ -253,6 +906,15 @@ pub struct BillingInvoice {
     pub billing_period_end: DateTime<Utc>,
     pub line_items: Vec<BillingLineItem>,
     pub usage_summary: Option<serde_json::Value>,
+    /// No tax was charged because the customer accounts for the VAT
+    #[serde(default)]
+    pub reverse_charge: bool,
+    /// Tax statement printed on the invoice, e.g. the reverse-charge notice
+    #[serde(default)]
+    pub tax_note: Option<String>,
+    /// Rate to the default currency when the invoice is in another currency
+    #[serde(default)]
+    pub exchange_rate: Option<InvoiceExchangeRate>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -262,6 +924,64 @@ pub struct BillingLineItem {
     pub unit_price: Decimal,
     pub total_price: Decimal,
     pub item_type: String, // 'subscription', 'usage', 'overage', 'tax'
+    #[serde(default)]
+    pub tax_amount: Decimal,
+    #[serde(default)]
+    pub tax_breakdown: Vec<TaxBreakdown>,
+}
+
+/// An hour of a marketplace module's usage, as reported by module-service
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RecordModuleUsageRequest {
+    pub tenant_id: Uuid,
+    pub module_id: String,
+    pub instance_id: Uuid,
+    pub developer_id: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub currency: String,
+    pub lines: Vec<ModuleUsageLine>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ModuleUsageLine {
+    pub metric: String,
+    pub quantity: f64,
+    pub unit_price: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeveloperRevenueReport {
+    pub developer_id: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub modules: Vec<ModuleRevenue>,
+}
+
+#[derive(Debug, Serialize, Deserialize, FromRow)]
+pub struct ModuleRevenue {
+    pub module_id: String,
+    pub currency: String,
+    pub amount: Decimal,
+    pub developer_amount: Decimal,
+    pub platform_amount: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct CurrencyBillingTotal {
+    pub currency: String,
+    pub billed: Decimal,
+    pub paid: Decimal,
+    pub outstanding: Decimal,
+}
+
+#[derive(Debug, FromRow)]
+pub struct TenantBillingTotal {
+    pub tenant_id: Uuid,
+    pub currency: String,
+    pub billed: Decimal,
+    pub paid: Decimal,
+    pub outstanding: Decimal,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -293,6 +1013,56 @@ pub struct BillingIssue {
     pub resolved: bool,
 }
 
+impl QuotaDefinition {
+    pub fn limit_for(&self, tier: &SubscriptionTier) -> i64 {
+        match tier {
+            SubscriptionTier::Free => self.free_limit,
+            SubscriptionTier::Professional => self.professional_limit,
+            SubscriptionTier::Enterprise => self.enterprise_limit,
+            SubscriptionTier::Custom => self.enterprise_limit, // Default to enterprise for custom
+        }
+    }
+}
+
+impl QuotaResetPeriod {
+    /// Start and end of the period `at` falls in, None for quotas that never reset
+    pub fn window(&self, at: DateTime<Utc>) -> Option<(DateTime<Utc>, DateTime<Utc>)> {
+        let hour_start = at
+            .with_minute(0)
+            .and_then(|t| t.with_second(0))
+            .and_then(|t| t.with_nanosecond(0))?;
+
+        match self {
+            QuotaResetPeriod::Never => None,
+            QuotaResetPeriod::Hourly => Some((hour_start, hour_start + Duration::hours(1))),
+            QuotaResetPeriod::Daily => {
+                let day_start = hour_start.with_hour(0)?;
+                Some((day_start, day_start + Duration::days(1)))
+            }
+            QuotaResetPeriod::Monthly => {
+                let month_start = Utc.with_ymd_and_hms(at.year(), at.month(), 1, 0, 0, 0).single()?;
+                let (year, month) = match at.month() {
+                    12 => (at.year() + 1, 1),
+                    month => (at.year(), month + 1),
+                };
+                let next_month = Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0).single()?;
+                Some((month_start, next_month))
+            }
+        }
+    }
+}
+
+impl BillingCycle {
+    /// Length of one billing period, None for cycles without one
+    pub fn period_days(&self) -> Option<i64> {
+        match self {
+            BillingCycle::Monthly | BillingCycle::UsageBased => Some(30),
+            BillingCycle::Yearly => Some(365),
+            BillingCycle::OneTime => None,
+        }
+    }
+}
+
 impl License {
     pub fn is_active(&self) -> bool {
         matches!(self.status, LicenseStatus::Active) &&
@@ -314,6 +1084,54 @@ impl License {
             false
         }
     }
+    
+    /// When the license stopped being active: suspended, cancelled or
+    /// expired. Pending licenses have not started, so they have not lapsed.
+    pub fn lapsed_since(&self) -> Option<DateTime<Utc>> {
+        if self.is_active() {
+            return None;
+        }
+        match self.status {
+            LicenseStatus::Pending => None,
+            LicenseStatus::Active => self.expires_at,
+            _ => Some(self.lapsed_at.or(self.expires_at.filter(|exp| *exp <= Utc::now())).unwrap_or(self.updated_at)),
+        }
+    }
+}
+
+impl SeatReconciliation {
+    pub fn drifted(&self) -> bool {
+        self.assigned_seats != self.recorded_usage
+            || self.billed_seats.map_or(false, |billed| billed != self.assigned_seats)
+    }
+}
+
+impl SubscriptionTier {
+    /// The tier's database name, as listed in coupon eligibility
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SubscriptionTier::Free => "free",
+            SubscriptionTier::Professional => "professional",
+            SubscriptionTier::Enterprise => "enterprise",
+            SubscriptionTier::Custom => "custom",
+        }
+    }
+}
+
+impl BillingDocumentType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BillingDocumentType::Invoice => "invoice",
+            BillingDocumentType::Receipt => "receipt",
+        }
+    }
+}
+
+impl DunningCase {
+    /// Still being retried or held by an operator
+    pub fn is_open(&self) -> bool {
+        matches!(self.status, DunningStatus::Active | DunningStatus::Paused)
+    }
 }
 
 impl TenantQuota {
@@ -0,0 +1,65 @@
+use serde::Deserialize;
+use uuid::Uuid;
+
+use crate::{
+    error::{LicenseError, Result},
+    models::ModuleEntitlement,
+};
+
+#[derive(Debug, Deserialize)]
+struct ModuleResponse<T> {
+    data: Option<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TenantModule {
+    module_id: String,
+    version: serde_json::Value,
+    status: String,
+}
+
+/// Module-service holds the marketplace modules each tenant has bought and
+/// installed; their licenses are part of the tenant's entitlements.
+#[derive(Debug, Clone)]
+pub struct ModuleServiceClient {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl ModuleServiceClient {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Modules the tenant holds a license for. Failed and uninstalling
+    /// installations hold none.
+    pub async fn get_module_entitlements(&self, tenant_id: Uuid) -> Result<Vec<ModuleEntitlement>> {
+        let response = self.client
+            .get(&format!("{}/api/v1/tenants/{}/modules", self.base_url, tenant_id))
+            .header("X-Tenant-ID", tenant_id.to_string())
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(LicenseError::Internal(format!("Module service lookup failed: {}", error_text)));
+        }
+
+        let modules: ModuleResponse<Vec<TenantModule>> = response.json().await?;
+        Ok(modules.data.unwrap_or_default()
+            .into_iter()
+            .filter(|module| !matches!(module.status.as_str(), "Failed" | "Uninstalling"))
+            .map(|module| ModuleEntitlement {
+                active: module.status == "Active",
+                version: match module.version {
+                    serde_json::Value::String(version) => version,
+                    version => version.to_string(),
+                },
+                module_id: module.module_id,
+            })
+            .collect())
+    }
+}
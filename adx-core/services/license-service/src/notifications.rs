@@ -0,0 +1,33 @@
+use adx_shared::email::{EmailSender, HttpEmailSender, SendEmailRequest};
+use std::sync::Arc;
+
+use crate::{
+    config::NotificationConfig,
+    error::{LicenseError, Result},
+};
+
+/// Sends billing emails to tenant admins as the tenant's branded system
+/// emails, through white-label-service
+#[derive(Clone)]
+pub struct EmailClient {
+    sender: Arc<dyn EmailSender>,
+}
+
+impl EmailClient {
+    pub fn new(config: NotificationConfig) -> Self {
+        Self {
+            sender: Arc::new(HttpEmailSender::new(
+                &config.white_label_service_url,
+                &config.internal_api_token,
+            )),
+        }
+    }
+
+    pub async fn send(&self, request: SendEmailRequest) -> Result<()> {
+        self.sender
+            .send(&request)
+            .await
+            .map_err(|e| LicenseError::Internal(format!("Email delivery failed: {}", e)))?;
+        Ok(())
+    }
+}
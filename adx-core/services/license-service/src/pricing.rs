@@ -0,0 +1,334 @@
+use std::collections::HashMap;
+
+use chrono::{DateTime, Duration, NaiveDate, Utc};
+use rust_decimal::{prelude::ToPrimitive, Decimal};
+use serde::Deserialize;
+
+use crate::{
+    config::CurrencyConfig,
+    error::{LicenseError, Result},
+    models::{BillingCycle, ExchangeRate},
+};
+
+/// Currency codes are stored as three upper case letters
+pub fn normalize_currency(currency: &str) -> Result<String> {
+    let currency = currency.trim().to_uppercase();
+    if currency.len() != 3 || !currency.chars().all(|c| c.is_ascii_alphabetic()) {
+        return Err(LicenseError::ValidationError(format!("{} is not an ISO 4217 currency code", currency)));
+    }
+    Ok(currency)
+}
+
+/// Currencies without a minor unit, as Stripe counts them
+const ZERO_DECIMAL_CURRENCIES: [&str; 16] = [
+    "BIF", "CLP", "DJF", "GNF", "JPY", "KMF", "KRW", "MGA", "PYG", "RWF", "UGX", "VND", "VUV", "XAF", "XOF", "XPF",
+];
+
+/// Currencies with three decimal places
+const THREE_DECIMAL_CURRENCIES: [&str; 7] = ["BHD", "IQD", "JOD", "KWD", "LYD", "OMR", "TND"];
+
+/// Decimal places in a currency's minor unit
+pub fn minor_units(currency: &str) -> u32 {
+    let currency = currency.trim().to_uppercase();
+    if ZERO_DECIMAL_CURRENCIES.contains(&currency.as_str()) {
+        0
+    } else if THREE_DECIMAL_CURRENCIES.contains(&currency.as_str()) {
+        3
+    } else {
+        2
+    }
+}
+
+/// Amounts are stored to two decimal places, which can't hold a
+/// three-decimal currency
+pub fn check_billable_currency(currency: &str) -> Result<()> {
+    if minor_units(currency) > 2 {
+        return Err(LicenseError::ValidationError(format!(
+            "{} has three decimal places, which billing does not support",
+            currency
+        )));
+    }
+    Ok(())
+}
+
+/// Round an amount to what the currency can express
+pub fn round_to_currency(amount: Decimal, currency: &str) -> Decimal {
+    amount.round_dp(minor_units(currency))
+}
+
+/// An amount in the currency's minor unit, as payment providers take it
+pub fn to_minor_units(amount: Decimal, currency: &str) -> i64 {
+    let scale = Decimal::from(10i64.pow(minor_units(currency)));
+    (amount * scale).round().to_i64().unwrap_or(0)
+}
+
+/// An amount from the currency's minor unit
+pub fn from_minor_units(value: i64, currency: &str) -> Decimal {
+    Decimal::new(value, minor_units(currency))
+}
+
+/// An amount of the rate's base currency in its quote currency
+pub fn convert(amount: Decimal, rate: &ExchangeRate) -> Decimal {
+    round_to_currency(amount * rate.rate, &rate.quote_currency)
+}
+
+/// An amount of the rate's quote currency in its base currency
+pub fn convert_to_base(amount: Decimal, rate: &ExchangeRate) -> Decimal {
+    if rate.rate.is_zero() {
+        return Decimal::ZERO;
+    }
+    round_to_currency(amount / rate.rate, &rate.base_currency)
+}
+
+/// What changing plan costs over what is left of the current period
+#[derive(Debug, Clone, PartialEq)]
+pub struct Proration {
+    pub days_remaining: i64,
+    /// Credit for the part of the current period not used yet
+    pub unused_credit: Decimal,
+    pub new_charge: Decimal,
+    /// Set when the change starts a new billing period
+    pub new_period_end: Option<DateTime<Utc>>,
+}
+
+impl Proration {
+    /// Negative when the change leaves a credit
+    pub fn amount_due(&self) -> Decimal {
+        self.new_charge - self.unused_credit
+    }
+}
+
+/// Prorate a move from the current price and cycle to new ones. The same
+/// cycle charges the new price for the rest of the period; a new cycle
+/// starts a full period now.
+pub fn prorate(
+    current_price: Decimal,
+    current_cycle: &BillingCycle,
+    expires_at: Option<DateTime<Utc>>,
+    new_price: Decimal,
+    new_cycle: &BillingCycle,
+    currency: &str,
+    now: DateTime<Utc>,
+) -> Proration {
+    let (days_remaining, unused_credit) = match (current_cycle.period_days(), expires_at) {
+        (Some(period_days), Some(expires_at)) => {
+            let days_remaining = (expires_at - now).num_days().clamp(0, period_days);
+            (days_remaining, current_price * Decimal::from(days_remaining) / Decimal::from(period_days))
+        }
+        (Some(_), None) => (0, Decimal::ZERO),
+        (None, _) => (0, current_price), // One-time licenses are credited in full
+    };
+
+    let (new_charge, new_period_end) = if new_cycle == current_cycle {
+        match new_cycle.period_days() {
+            Some(period_days) => (new_price * Decimal::from(days_remaining) / Decimal::from(period_days), None),
+            None => (new_price, None),
+        }
+    } else {
+        (new_price, new_cycle.period_days().map(|days| now + Duration::days(days)))
+    };
+
+    Proration {
+        days_remaining,
+        unused_credit: round_to_currency(unused_credit, currency),
+        new_charge: round_to_currency(new_charge, currency),
+        new_period_end,
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct LatestRates {
+    date: NaiveDate,
+    rates: HashMap<String, Decimal>,
+}
+
+/// Fetches reference exchange rates from a Frankfurter-compatible API
+#[derive(Debug, Clone)]
+pub struct ExchangeRateClient {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl ExchangeRateClient {
+    pub fn new(config: &CurrencyConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: config.exchange_rate_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub fn source(&self) -> &'static str {
+        "frankfurter"
+    }
+
+    /// Today's rates of `currencies` against `base_currency`, and the day
+    /// they were published for
+    pub async fn latest(&self, base_currency: &str, currencies: &[String]) -> Result<(DateTime<Utc>, Vec<(String, Decimal)>)> {
+        let response = self.client
+            .get(&format!("{}/latest", self.base_url))
+            .query(&[("from", base_currency), ("to", &currencies.join(","))])
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(LicenseError::Internal(format!("Exchange rate lookup failed: {}", error_text)));
+        }
+
+        let latest: LatestRates = response.json().await?;
+        let effective_at = latest.date.and_hms_opt(0, 0, 0)
+            .map(|at| at.and_utc())
+            .unwrap_or_else(Utc::now);
+        Ok((effective_at, latest.rates.into_iter().collect()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    fn now() -> DateTime<Utc> {
+        Utc.with_ymd_and_hms(2025, 3, 1, 12, 0, 0).unwrap()
+    }
+
+    fn rate(base: &str, quote: &str, rate: Decimal) -> ExchangeRate {
+        ExchangeRate {
+            id: uuid::Uuid::new_v4(),
+            base_currency: base.to_string(),
+            quote_currency: quote.to_string(),
+            rate,
+            source: "test".to_string(),
+            effective_at: now(),
+            created_at: now(),
+        }
+    }
+
+    #[test]
+    fn test_convert_rounds_to_the_quote_currency() {
+        let usd_eur = rate("USD", "EUR", Decimal::new(92345, 5));
+        assert_eq!(convert(Decimal::new(9900, 2), &usd_eur), Decimal::new(9142, 2));
+        assert_eq!(convert_to_base(Decimal::new(9142, 2), &usd_eur), Decimal::new(9900, 2));
+
+        let usd_jpy = rate("USD", "JPY", Decimal::new(151234, 3));
+        assert_eq!(convert(Decimal::new(9900, 2), &usd_jpy), Decimal::from(14972));
+        assert_eq!(convert_to_base(Decimal::from(14972), &usd_jpy), Decimal::new(9900, 2));
+
+        let usd_krw = rate("USD", "KRW", Decimal::new(13625, 1));
+        assert_eq!(convert(Decimal::new(2999, 2), &usd_krw), Decimal::from(40861));
+
+        assert_eq!(convert_to_base(Decimal::ONE, &rate("USD", "EUR", Decimal::ZERO)), Decimal::ZERO);
+    }
+
+    #[test]
+    fn test_minor_units() {
+        assert_eq!(to_minor_units(Decimal::new(1999, 2), "usd"), 1999);
+        assert_eq!(to_minor_units(Decimal::from(14972), "JPY"), 14972);
+        assert_eq!(from_minor_units(14972, "JPY"), Decimal::from(14972));
+        assert_eq!(from_minor_units(1999, "EUR"), Decimal::new(1999, 2));
+        assert_eq!(round_to_currency(Decimal::new(14972499, 3), "KRW"), Decimal::from(14972));
+
+        assert!(check_billable_currency("JPY").is_ok());
+        assert!(check_billable_currency("KWD").is_err());
+    }
+
+    #[test]
+    fn test_downgrade_mid_period_leaves_a_credit() {
+        let proration = prorate(
+            Decimal::new(9900, 2),
+            &BillingCycle::Monthly,
+            Some(now() + Duration::days(15)),
+            Decimal::new(2900, 2),
+            &BillingCycle::Monthly,
+            "USD",
+            now(),
+        );
+
+        assert_eq!(proration.days_remaining, 15);
+        assert_eq!(proration.unused_credit, Decimal::new(4950, 2));
+        assert_eq!(proration.new_charge, Decimal::new(1450, 2));
+        assert_eq!(proration.amount_due(), Decimal::new(-3500, 2));
+        assert_eq!(proration.new_period_end, None);
+    }
+
+    #[test]
+    fn test_upgrade_charges_the_difference_for_the_rest_of_the_period() {
+        let proration = prorate(
+            Decimal::new(2900, 2),
+            &BillingCycle::Monthly,
+            Some(now() + Duration::days(10)),
+            Decimal::new(9900, 2),
+            &BillingCycle::Monthly,
+            "USD",
+            now(),
+        );
+
+        assert_eq!(proration.unused_credit, Decimal::new(967, 2));
+        assert_eq!(proration.new_charge, Decimal::new(3300, 2));
+        assert_eq!(proration.amount_due(), Decimal::new(2333, 2));
+    }
+
+    #[test]
+    fn test_cycle_change_starts_a_full_period() {
+        let proration = prorate(
+            Decimal::new(9900, 2),
+            &BillingCycle::Monthly,
+            Some(now() + Duration::days(30)),
+            Decimal::new(99000, 2),
+            &BillingCycle::Yearly,
+            "USD",
+            now(),
+        );
+
+        assert_eq!(proration.unused_credit, Decimal::new(9900, 2));
+        assert_eq!(proration.new_charge, Decimal::new(99000, 2));
+        assert_eq!(proration.new_period_end, Some(now() + Duration::days(365)));
+    }
+
+    #[test]
+    fn test_remaining_days_are_clamped_to_the_period() {
+        // Expired: nothing left to credit or charge on the same cycle
+        let expired = prorate(
+            Decimal::new(9900, 2),
+            &BillingCycle::Monthly,
+            Some(now() - Duration::days(3)),
+            Decimal::new(2900, 2),
+            &BillingCycle::Monthly,
+            "USD",
+            now(),
+        );
+        assert_eq!(expired.days_remaining, 0);
+        assert_eq!(expired.amount_due(), Decimal::ZERO);
+
+        // Paid well ahead: at most one period is credited
+        let ahead = prorate(
+            Decimal::new(9900, 2),
+            &BillingCycle::Monthly,
+            Some(now() + Duration::days(90)),
+            Decimal::new(2900, 2),
+            &BillingCycle::Monthly,
+            "USD",
+            now(),
+        );
+        assert_eq!(ahead.days_remaining, 30);
+        assert_eq!(ahead.unused_credit, Decimal::new(9900, 2));
+    }
+
+    #[test]
+    fn test_one_time_license_is_credited_in_full() {
+        let proration = prorate(
+            Decimal::new(50000, 2),
+            &BillingCycle::OneTime,
+            None,
+            Decimal::new(9900, 2),
+            &BillingCycle::Monthly,
+            "USD",
+            now(),
+        );
+
+        assert_eq!(proration.unused_credit, Decimal::new(50000, 2));
+        assert_eq!(proration.new_charge, Decimal::new(9900, 2));
+        assert_eq!(proration.amount_due(), Decimal::new(-40100, 2));
+        assert_eq!(proration.new_period_end, Some(now() + Duration::days(30)));
+    }
+}
@@ -0,0 +1,90 @@
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+use crate::{
+    error::{LicenseError, Result},
+    models::{Coupon, CouponDiscountType, CreateCouponRequest, SubscriptionTier},
+};
+
+/// Coupon codes are matched ignoring case and surrounding whitespace
+pub fn normalize_code(code: &str) -> String {
+    code.trim().to_uppercase()
+}
+
+/// Refuse coupons that could never be redeemed
+pub fn validate_new_coupon(request: &CreateCouponRequest) -> Result<()> {
+    let code = normalize_code(&request.code);
+    if code.is_empty() || code.len() > 50 || !code.chars().all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_') {
+        return Err(LicenseError::ValidationError(
+            "Coupon codes are 1 to 50 letters, digits, dashes or underscores".to_string(),
+        ));
+    }
+    if request.discount_value <= Decimal::ZERO {
+        return Err(LicenseError::ValidationError("Coupon discounts must be positive".to_string()));
+    }
+    match request.discount_type {
+        CouponDiscountType::Percentage if request.discount_value > Decimal::ONE_HUNDRED => {
+            return Err(LicenseError::ValidationError("Percentage coupons take at most 100% off".to_string()));
+        }
+        CouponDiscountType::Fixed if request.currency.is_none() => {
+            return Err(LicenseError::ValidationError("Fixed coupons need a currency".to_string()));
+        }
+        _ => {}
+    }
+    if request.max_redemptions.is_some_and(|max| max < 1) || request.max_redemptions_per_tenant.is_some_and(|max| max < 1) {
+        return Err(LicenseError::ValidationError("Redemption limits must be at least 1".to_string()));
+    }
+    if let (Some(from), Some(until)) = (request.valid_from, request.valid_until) {
+        if until <= from {
+            return Err(LicenseError::ValidationError("Coupons must be valid until after they are valid from".to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// Check the coupon can be used by a tenant that has redeemed it
+/// `tenant_redemptions` times, on a charge in `currency` for `tier`
+pub fn check_redeemable(
+    coupon: &Coupon,
+    tier: &SubscriptionTier,
+    currency: &str,
+    tenant_redemptions: i64,
+    at: DateTime<Utc>,
+) -> Result<()> {
+    let refuse = |reason: &str| Err(LicenseError::InvalidCoupon(format!("{}: {}", coupon.code, reason)));
+
+    if !coupon.active {
+        return refuse("the coupon is no longer offered");
+    }
+    if at < coupon.valid_from {
+        return refuse("the coupon is not valid yet");
+    }
+    if coupon.valid_until.is_some_and(|until| at >= until) {
+        return refuse("the coupon has expired");
+    }
+    if coupon.max_redemptions.is_some_and(|max| coupon.redemption_count >= max) {
+        return refuse("the coupon has been used up");
+    }
+    if tenant_redemptions >= coupon.max_redemptions_per_tenant as i64 {
+        return refuse("the tenant has already used the coupon");
+    }
+    if !coupon.eligible_tiers.is_empty() && !coupon.eligible_tiers.iter().any(|eligible| eligible == tier.as_str()) {
+        return refuse("the coupon does not apply to this plan");
+    }
+    if coupon.currency.as_deref().is_some_and(|coupon_currency| !coupon_currency.eq_ignore_ascii_case(currency)) {
+        return refuse("the coupon is for charges in another currency");
+    }
+    Ok(())
+}
+
+/// The coupon's discount on a charge of `amount`, never more than the charge
+pub fn discount_for(coupon: &Coupon, amount: Decimal) -> Decimal {
+    if amount <= Decimal::ZERO {
+        return Decimal::ZERO;
+    }
+    let discount = match coupon.discount_type {
+        CouponDiscountType::Percentage => amount * coupon.discount_value / Decimal::ONE_HUNDRED,
+        CouponDiscountType::Fixed => coupon.discount_value,
+    };
+    discount.round_dp(2).min(amount)
+}
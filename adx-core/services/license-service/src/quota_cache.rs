@@ -0,0 +1,149 @@
+use chrono::{DateTime, Utc};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+use crate::{error::Result, models::QuotaResetPeriod};
+
+/// Counters of quotas that never reset are dropped after this long, so that
+/// usage changed in the database directly is picked up again
+const ALLOCATION_COUNTER_TTL_SECONDS: i64 = 3600;
+
+/// Seeds a missing counter, then adds to it unless that takes it over the
+/// hard limit. Returns the usage and 1 when applied, 0 when refused and -1
+/// when the counter has to be seeded first.
+const UPDATE_COUNTER_SCRIPT: &str = r#"
+if redis.call('EXISTS', KEYS[1]) == 0 then
+    if ARGV[4] == '' then
+        return {0, -1}
+    end
+    redis.call('SET', KEYS[1], ARGV[4], 'EX', ARGV[3])
+end
+
+local amount = tonumber(ARGV[1])
+local limit = tonumber(ARGV[2])
+local usage = redis.call('INCRBY', KEYS[1], amount)
+
+if amount > 0 and limit >= 0 and usage > limit then
+    return {redis.call('DECRBY', KEYS[1], amount), 0}
+end
+if usage < 0 then
+    redis.call('SET', KEYS[1], 0, 'KEEPTTL')
+    usage = 0
+end
+return {usage, 1}
+"#;
+
+/// A tenant's limit on one quota, as cached for real-time checks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CachedQuotaLimit {
+    pub quota_limit: i64,
+    pub enforce_hard_limit: bool,
+    pub warning_threshold_percent: i32,
+    pub reset_period: QuotaResetPeriod,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum CounterUpdate {
+    Applied(i64),
+    /// Over the hard limit; the counter is unchanged
+    Refused(i64),
+    /// No counter for the period yet
+    Unseeded,
+}
+
+/// Redis counters behind `/quotas/consume`. The database remains the record
+/// of usage: counters are seeded from it and expire with their period.
+#[derive(Clone)]
+pub struct QuotaCache {
+    client: redis::Client,
+    limit_ttl_seconds: u64,
+}
+
+impl QuotaCache {
+    pub fn new(redis_url: &str, limit_ttl_seconds: u64) -> Result<Self> {
+        Ok(Self {
+            client: redis::Client::open(redis_url)?,
+            limit_ttl_seconds,
+        })
+    }
+
+    pub async fn get_limit(&self, tenant_id: Uuid, quota_name: &str) -> Result<Option<CachedQuotaLimit>> {
+        let mut conn = self.client.get_async_connection().await?;
+        let cached: Option<String> = conn.get(limit_key(tenant_id, quota_name)).await?;
+        Ok(cached.and_then(|cached| serde_json::from_str(&cached).ok()))
+    }
+
+    pub async fn put_limit(&self, tenant_id: Uuid, quota_name: &str, limit: &CachedQuotaLimit) -> Result<()> {
+        let mut conn = self.client.get_async_connection().await?;
+        conn.set_ex::<_, _, ()>(
+            limit_key(tenant_id, quota_name),
+            serde_json::to_string(limit)?,
+            self.limit_ttl_seconds,
+        )
+        .await?;
+        Ok(())
+    }
+
+    /// Add `amount` to the counter of the period `at` falls in. A negative
+    /// amount gives usage back. `hard_limit` of -1 applies no limit;
+    /// `seed` is the usage to start a missing counter from.
+    pub async fn update(
+        &self,
+        tenant_id: Uuid,
+        quota_name: &str,
+        reset_period: QuotaResetPeriod,
+        at: DateTime<Utc>,
+        amount: i64,
+        hard_limit: i64,
+        seed: Option<i64>,
+    ) -> Result<CounterUpdate> {
+        let (key, ttl) = counter_key(tenant_id, quota_name, reset_period, at);
+        let mut conn = self.client.get_async_connection().await?;
+
+        let (usage, status): (i64, i64) = redis::Script::new(UPDATE_COUNTER_SCRIPT)
+            .key(key)
+            .arg(amount)
+            .arg(hard_limit)
+            .arg(ttl)
+            .arg(seed.map(|seed| seed.to_string()).unwrap_or_default())
+            .invoke_async(&mut conn)
+            .await?;
+
+        Ok(match status {
+            1 => CounterUpdate::Applied(usage),
+            0 => CounterUpdate::Refused(usage),
+            _ => CounterUpdate::Unseeded,
+        })
+    }
+
+    /// Drop the cached limit and current counter, e.g. after a reset
+    pub async fn invalidate(&self, tenant_id: Uuid, quota_name: &str, reset_period: QuotaResetPeriod) -> Result<()> {
+        let (counter, _) = counter_key(tenant_id, quota_name, reset_period, Utc::now());
+        let mut conn = self.client.get_async_connection().await?;
+        redis::pipe()
+            .del(limit_key(tenant_id, quota_name))
+            .del(counter)
+            .query_async::<_, ()>(&mut conn)
+            .await?;
+        Ok(())
+    }
+}
+
+fn limit_key(tenant_id: Uuid, quota_name: &str) -> String {
+    format!("quota:limit:{}:{}", tenant_id, quota_name)
+}
+
+/// Key of the counter for the period `at` falls in, and how long it lives
+fn counter_key(tenant_id: Uuid, quota_name: &str, reset_period: QuotaResetPeriod, at: DateTime<Utc>) -> (String, i64) {
+    match reset_period.window(at) {
+        Some((start, end)) => (
+            format!("quota:usage:{}:{}:{}", tenant_id, quota_name, start.timestamp()),
+            (end - at).num_seconds().max(1),
+        ),
+        None => (
+            format!("quota:usage:{}:{}", tenant_id, quota_name),
+            ALLOCATION_COUNTER_TTL_SECONDS,
+        ),
+    }
+}
@@ -1,4 +1,5 @@
 use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
 use sqlx::{PgPool, Row};
 use uuid::Uuid;
 
@@ -36,7 +37,7 @@ impl LicenseRepository {
                 billing_cycle as "billing_cycle: BillingCycle",
                 base_price, currency, starts_at, expires_at, auto_renew,
                 features, custom_quotas, stripe_subscription_id, stripe_customer_id,
-                paypal_subscription_id, created_at, updated_at, created_by
+                paypal_subscription_id, lapsed_at, created_at, updated_at, created_by
             "#,
             request.tenant_id,
             license_key,
@@ -65,7 +66,7 @@ impl LicenseRepository {
                 billing_cycle as "billing_cycle: BillingCycle",
                 base_price, currency, starts_at, expires_at, auto_renew,
                 features, custom_quotas, stripe_subscription_id, stripe_customer_id,
-                paypal_subscription_id, created_at, updated_at, created_by
+                paypal_subscription_id, lapsed_at, created_at, updated_at, created_by
             FROM licenses 
             WHERE id = $1
             "#,
@@ -88,7 +89,7 @@ impl LicenseRepository {
                 billing_cycle as "billing_cycle: BillingCycle",
                 base_price, currency, starts_at, expires_at, auto_renew,
                 features, custom_quotas, stripe_subscription_id, stripe_customer_id,
-                paypal_subscription_id, created_at, updated_at, created_by
+                paypal_subscription_id, lapsed_at, created_at, updated_at, created_by
             FROM licenses 
             WHERE tenant_id = $1 AND status = 'active'
             ORDER BY created_at DESC
@@ -102,6 +103,32 @@ impl LicenseRepository {
         Ok(license)
     }
 
+    /// The tenant's latest license, whatever its status
+    pub async fn get_latest_by_tenant_id(&self, tenant_id: Uuid) -> Result<Option<License>> {
+        let license = sqlx::query_as!(
+            License,
+            r#"
+            SELECT 
+                id, tenant_id, license_key,
+                subscription_tier as "subscription_tier: SubscriptionTier",
+                status as "status: LicenseStatus",
+                billing_cycle as "billing_cycle: BillingCycle",
+                base_price, currency, starts_at, expires_at, auto_renew,
+                features, custom_quotas, stripe_subscription_id, stripe_customer_id,
+                paypal_subscription_id, lapsed_at, created_at, updated_at, created_by
+            FROM licenses 
+            WHERE tenant_id = $1
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+            tenant_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(license)
+    }
+
     pub async fn get_by_license_key(&self, license_key: &str) -> Result<Option<License>> {
         let license = sqlx::query_as!(
             License,
@@ -113,7 +140,7 @@ impl LicenseRepository {
                 billing_cycle as "billing_cycle: BillingCycle",
                 base_price, currency, starts_at, expires_at, auto_renew,
                 features, custom_quotas, stripe_subscription_id, stripe_customer_id,
-                paypal_subscription_id, created_at, updated_at, created_by
+                paypal_subscription_id, lapsed_at, created_at, updated_at, created_by
             FROM licenses 
             WHERE license_key = $1
             "#,
@@ -139,6 +166,11 @@ impl LicenseRepository {
                 auto_renew = COALESCE($6, auto_renew),
                 features = COALESCE($7, features),
                 custom_quotas = COALESCE($8, custom_quotas),
+                billing_cycle = COALESCE($9, billing_cycle),
+                lapsed_at = CASE
+                    WHEN COALESCE($3, status) IN ('expired', 'suspended', 'cancelled') THEN COALESCE(lapsed_at, NOW())
+                    ELSE NULL
+                END,
                 updated_at = NOW()
             WHERE id = $1
             RETURNING 
@@ -148,7 +180,7 @@ impl LicenseRepository {
                 billing_cycle as "billing_cycle: BillingCycle",
                 base_price, currency, starts_at, expires_at, auto_renew,
                 features, custom_quotas, stripe_subscription_id, stripe_customer_id,
-                paypal_subscription_id, created_at, updated_at, created_by
+                paypal_subscription_id, lapsed_at, created_at, updated_at, created_by
             "#,
             id,
             request.subscription_tier as Option<SubscriptionTier>,
@@ -157,7 +189,8 @@ impl LicenseRepository {
             request.expires_at,
             request.auto_renew,
             features_json,
-            request.custom_quotas
+            request.custom_quotas,
+            request.billing_cycle as Option<BillingCycle>
         )
         .fetch_one(&self.pool)
         .await?;
@@ -165,6 +198,78 @@ impl LicenseRepository {
         Ok(license)
     }
 
+    /// The tenant's latest license billed to a Stripe customer, whatever its status
+    pub async fn get_by_stripe_customer_id(&self, customer_id: &str) -> Result<Option<License>> {
+        let license = sqlx::query_as!(
+            License,
+            r#"
+            SELECT 
+                id, tenant_id, license_key,
+                subscription_tier as "subscription_tier: SubscriptionTier",
+                status as "status: LicenseStatus",
+                billing_cycle as "billing_cycle: BillingCycle",
+                base_price, currency, starts_at, expires_at, auto_renew,
+                features, custom_quotas, stripe_subscription_id, stripe_customer_id,
+                paypal_subscription_id, lapsed_at, created_at, updated_at, created_by
+            FROM licenses 
+            WHERE stripe_customer_id = $1
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+            customer_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(license)
+    }
+
+    pub async fn get_by_stripe_subscription_id(&self, subscription_id: &str) -> Result<Option<License>> {
+        let license = sqlx::query_as!(
+            License,
+            r#"
+            SELECT 
+                id, tenant_id, license_key,
+                subscription_tier as "subscription_tier: SubscriptionTier",
+                status as "status: LicenseStatus",
+                billing_cycle as "billing_cycle: BillingCycle",
+                base_price, currency, starts_at, expires_at, auto_renew,
+                features, custom_quotas, stripe_subscription_id, stripe_customer_id,
+                paypal_subscription_id, lapsed_at, created_at, updated_at, created_by
+            FROM licenses 
+            WHERE stripe_subscription_id = $1
+            "#,
+            subscription_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(license)
+    }
+
+    pub async fn get_by_paypal_subscription_id(&self, subscription_id: &str) -> Result<Option<License>> {
+        let license = sqlx::query_as!(
+            License,
+            r#"
+            SELECT 
+                id, tenant_id, license_key,
+                subscription_tier as "subscription_tier: SubscriptionTier",
+                status as "status: LicenseStatus",
+                billing_cycle as "billing_cycle: BillingCycle",
+                base_price, currency, starts_at, expires_at, auto_renew,
+                features, custom_quotas, stripe_subscription_id, stripe_customer_id,
+                paypal_subscription_id, lapsed_at, created_at, updated_at, created_by
+            FROM licenses 
+            WHERE paypal_subscription_id = $1
+            "#,
+            subscription_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(license)
+    }
+
     pub async fn get_expiring_licenses(&self, days_ahead: i32) -> Result<Vec<License>> {
         let licenses = sqlx::query_as!(
             License,
@@ -176,7 +281,7 @@ impl LicenseRepository {
                 billing_cycle as "billing_cycle: BillingCycle",
                 base_price, currency, starts_at, expires_at, auto_renew,
                 features, custom_quotas, stripe_subscription_id, stripe_customer_id,
-                paypal_subscription_id, created_at, updated_at, created_by
+                paypal_subscription_id, lapsed_at, created_at, updated_at, created_by
             FROM licenses 
             WHERE status = 'active' 
             AND expires_at IS NOT NULL 
@@ -214,7 +319,16 @@ impl QuotaRepository {
     pub async fn get_quota_definitions(&self) -> Result<Vec<QuotaDefinition>> {
         let definitions = sqlx::query_as!(
             QuotaDefinition,
-            "SELECT * FROM quota_definitions ORDER BY category, name"
+            r#"
+            SELECT
+                id, name, description, unit, category,
+                reset_period as "reset_period: QuotaResetPeriod",
+                free_limit, professional_limit, enterprise_limit,
+                enforce_hard_limit, warning_threshold_percent,
+                created_at, updated_at
+            FROM quota_definitions
+            ORDER BY category, name
+            "#
         )
         .fetch_all(&self.pool)
         .await?;
@@ -225,7 +339,16 @@ impl QuotaRepository {
     pub async fn get_quota_definition_by_name(&self, name: &str) -> Result<Option<QuotaDefinition>> {
         let definition = sqlx::query_as!(
             QuotaDefinition,
-            "SELECT * FROM quota_definitions WHERE name = $1",
+            r#"
+            SELECT
+                id, name, description, unit, category,
+                reset_period as "reset_period: QuotaResetPeriod",
+                free_limit, professional_limit, enterprise_limit,
+                enforce_hard_limit, warning_threshold_percent,
+                created_at, updated_at
+            FROM quota_definitions
+            WHERE name = $1
+            "#,
             name
         )
         .fetch_optional(&self.pool)
@@ -234,6 +357,80 @@ impl QuotaRepository {
         Ok(definition)
     }
 
+    pub async fn create_quota_definition(&self, request: CreateQuotaDefinitionRequest) -> Result<QuotaDefinition> {
+        let definition = sqlx::query_as!(
+            QuotaDefinition,
+            r#"
+            INSERT INTO quota_definitions (
+                name, description, unit, category, reset_period,
+                free_limit, professional_limit, enterprise_limit,
+                enforce_hard_limit, warning_threshold_percent
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, COALESCE($9, true), COALESCE($10, 80))
+            RETURNING
+                id, name, description, unit, category,
+                reset_period as "reset_period: QuotaResetPeriod",
+                free_limit, professional_limit, enterprise_limit,
+                enforce_hard_limit, warning_threshold_percent,
+                created_at, updated_at
+            "#,
+            request.name,
+            request.description,
+            request.unit,
+            request.category,
+            request.reset_period as QuotaResetPeriod,
+            request.free_limit,
+            request.professional_limit,
+            request.enterprise_limit,
+            request.enforce_hard_limit,
+            request.warning_threshold_percent
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(definition)
+    }
+
+    pub async fn update_quota_definition(&self, name: &str, request: UpdateQuotaDefinitionRequest) -> Result<Option<QuotaDefinition>> {
+        let definition = sqlx::query_as!(
+            QuotaDefinition,
+            r#"
+            UPDATE quota_definitions SET
+                description = COALESCE($2, description),
+                unit = COALESCE($3, unit),
+                category = COALESCE($4, category),
+                reset_period = COALESCE($5, reset_period),
+                free_limit = COALESCE($6, free_limit),
+                professional_limit = COALESCE($7, professional_limit),
+                enterprise_limit = COALESCE($8, enterprise_limit),
+                enforce_hard_limit = COALESCE($9, enforce_hard_limit),
+                warning_threshold_percent = COALESCE($10, warning_threshold_percent),
+                updated_at = NOW()
+            WHERE name = $1
+            RETURNING
+                id, name, description, unit, category,
+                reset_period as "reset_period: QuotaResetPeriod",
+                free_limit, professional_limit, enterprise_limit,
+                enforce_hard_limit, warning_threshold_percent,
+                created_at, updated_at
+            "#,
+            name,
+            request.description,
+            request.unit,
+            request.category,
+            request.reset_period as Option<QuotaResetPeriod>,
+            request.free_limit,
+            request.professional_limit,
+            request.enterprise_limit,
+            request.enforce_hard_limit,
+            request.warning_threshold_percent
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(definition)
+    }
+
     pub async fn get_tenant_quotas(&self, tenant_id: Uuid) -> Result<Vec<TenantQuota>> {
         let quotas = sqlx::query_as!(
             TenantQuota,
@@ -267,12 +464,7 @@ impl QuotaRepository {
         let definitions = self.get_quota_definitions().await?;
         
         for definition in definitions {
-            let quota_limit = match subscription_tier {
-                SubscriptionTier::Free => definition.free_limit,
-                SubscriptionTier::Professional => definition.professional_limit,
-                SubscriptionTier::Enterprise => definition.enterprise_limit,
-                SubscriptionTier::Custom => definition.enterprise_limit, // Default to enterprise for custom
-            };
+            let quota_limit = definition.limit_for(&subscription_tier);
 
             sqlx::query!(
                 r#"
@@ -291,12 +483,61 @@ impl QuotaRepository {
         Ok(())
     }
 
+    /// Limits the tenant's quotas would get on another tier. Quotas with a
+    /// custom limit keep it across plan changes and are left out.
+    pub async fn get_tier_limit_changes(&self, tenant_id: Uuid, subscription_tier: &SubscriptionTier) -> Result<Vec<QuotaLimitChange>> {
+        let quotas = self.get_tenant_quotas(tenant_id).await?;
+        let definitions = self.get_quota_definitions().await?;
+
+        let changes = quotas.into_iter()
+            .filter(|quota| quota.custom_limit.is_none())
+            .filter_map(|quota| {
+                let definition = definitions.iter().find(|d| d.id == quota.quota_definition_id)?;
+                Some(QuotaLimitChange {
+                    quota_name: definition.name.clone(),
+                    current_limit: quota.quota_limit,
+                    new_limit: definition.limit_for(subscription_tier),
+                    current_usage: quota.current_usage,
+                })
+            })
+            .filter(|change| change.new_limit != change.current_limit)
+            .collect();
+
+        Ok(changes)
+    }
+
+    pub async fn set_quota_limits(&self, tenant_id: Uuid, limits: &[(String, i64)]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for (quota_name, quota_limit) in limits {
+            sqlx::query!(
+                r#"
+                UPDATE tenant_quotas SET
+                    quota_limit = $3,
+                    updated_at = NOW()
+                FROM quota_definitions
+                WHERE tenant_quotas.quota_definition_id = quota_definitions.id
+                AND tenant_quotas.tenant_id = $1
+                AND quota_definitions.name = $2
+                "#,
+                tenant_id,
+                quota_name,
+                quota_limit
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
     pub async fn update_quota_usage(&self, tenant_id: Uuid, quota_name: &str, amount: i64) -> Result<TenantQuota> {
         let quota = sqlx::query_as!(
             TenantQuota,
             r#"
             UPDATE tenant_quotas SET
-                current_usage = current_usage + $3,
+                current_usage = GREATEST(current_usage + $3, 0),
                 updated_at = NOW()
             FROM quota_definitions
             WHERE tenant_quotas.quota_definition_id = quota_definitions.id
@@ -335,6 +576,52 @@ impl QuotaRepository {
         Ok(())
     }
 
+    /// Start a new period for a resetting quota. Usage is zeroed once per
+    /// period, however many callers notice the period has turned.
+    pub async fn roll_quota_window(&self, tenant_id: Uuid, quota_name: &str, window_start: DateTime<Utc>) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE tenant_quotas SET
+                current_usage = 0,
+                last_reset_at = $3,
+                updated_at = NOW()
+            FROM quota_definitions
+            WHERE tenant_quotas.quota_definition_id = quota_definitions.id
+            AND tenant_quotas.tenant_id = $1
+            AND quota_definitions.name = $2
+            AND tenant_quotas.last_reset_at < $3
+            "#,
+            tenant_id,
+            quota_name,
+            window_start
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn set_quota_usage(&self, tenant_id: Uuid, quota_name: &str, usage: i64) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE tenant_quotas SET
+                current_usage = $3,
+                updated_at = NOW()
+            FROM quota_definitions
+            WHERE tenant_quotas.quota_definition_id = quota_definitions.id
+            AND tenant_quotas.tenant_id = $1
+            AND quota_definitions.name = $2
+            "#,
+            tenant_id,
+            quota_name,
+            usage
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
     pub async fn log_usage(&self, request: QuotaUsageRequest) -> Result<UsageLog> {
         let definition = self.get_quota_definition_by_name(&request.quota_name).await?
             .ok_or_else(|| LicenseError::QuotaNotFound { quota_name: request.quota_name.clone() })?;
@@ -365,101 +652,2008 @@ impl QuotaRepository {
 }
 
 #[derive(Clone)]
-pub struct BillingRepository {
+pub struct SeatRepository {
     pool: PgPool,
 }
 
-impl BillingRepository {
+impl SeatRepository {
     pub fn new(pool: PgPool) -> Self {
         Self { pool }
     }
 
-    pub async fn create_billing_record(&self, record: BillingHistory) -> Result<BillingHistory> {
-        let billing_record = sqlx::query_as!(
-            BillingHistory,
+    /// Give a user one of the tenant's seats. Assigning a user who already
+    /// holds a seat returns their assignment without taking another.
+    pub async fn assign(&self, request: AssignSeatRequest) -> Result<SeatAssignment> {
+        let mut tx = self.pool.begin().await?;
+
+        // Lock the seat quota so concurrent assignments cannot both take the last seat
+        let quota = sqlx::query!(
             r#"
-            INSERT INTO billing_history (
-                tenant_id, license_id, invoice_number, amount, currency, tax_amount,
-                billing_period_start, billing_period_end, payment_status,
-                payment_method, payment_reference, usage_details
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
-            RETURNING 
-                id, tenant_id, license_id, invoice_number, amount, currency, tax_amount,
-                billing_period_start, billing_period_end,
-                payment_status as "payment_status: PaymentStatus",
-                payment_method, payment_reference, paid_at, usage_details,
-                created_at, updated_at
+            SELECT tq.quota_limit, tq.current_usage FROM tenant_quotas tq
+            JOIN quota_definitions qd ON tq.quota_definition_id = qd.id
+            WHERE tq.tenant_id = $1 AND qd.name = $2
+            FOR UPDATE OF tq
             "#,
-            record.tenant_id,
-            record.license_id,
-            record.invoice_number,
-            record.amount,
-            record.currency,
-            record.tax_amount,
-            record.billing_period_start,
-            record.billing_period_end,
-            record.payment_status as PaymentStatus,
-            record.payment_method,
-            record.payment_reference,
-            record.usage_details
+            request.tenant_id,
+            SEAT_QUOTA
         )
-        .fetch_one(&self.pool)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| LicenseError::QuotaNotFound { quota_name: SEAT_QUOTA.to_string() })?;
+
+        let existing = sqlx::query_as!(
+            SeatAssignment,
+            "SELECT * FROM seat_assignments WHERE tenant_id = $1 AND user_id = $2",
+            request.tenant_id,
+            request.user_id
+        )
+        .fetch_optional(&mut *tx)
         .await?;
+        if let Some(assignment) = existing {
+            return Ok(assignment);
+        }
 
-        Ok(billing_record)
-    }
+        if quota.quota_limit >= 0 && quota.current_usage >= quota.quota_limit {
+            return Err(LicenseError::QuotaExceeded {
+                quota_name: SEAT_QUOTA.to_string(),
+                current_usage: quota.current_usage,
+                quota_limit: quota.quota_limit,
+            });
+        }
 
-    pub async fn update_payment_status(&self, id: Uuid, status: PaymentStatus, payment_reference: Option<String>) -> Result<()> {
-        let paid_at = if matches!(status, PaymentStatus::Completed) {
-            Some(Utc::now())
-        } else {
-            None
-        };
+        let assignment = sqlx::query_as!(
+            SeatAssignment,
+            r#"
+            INSERT INTO seat_assignments (tenant_id, user_id, assigned_by)
+            VALUES ($1, $2, $3)
+            RETURNING *
+            "#,
+            request.tenant_id,
+            request.user_id,
+            request.assigned_by
+        )
+        .fetch_one(&mut *tx)
+        .await?;
 
         sqlx::query!(
             r#"
-            UPDATE billing_history SET
-                payment_status = $2,
-                payment_reference = COALESCE($3, payment_reference),
-                paid_at = $4,
+            UPDATE tenant_quotas SET
+                current_usage = current_usage + 1,
                 updated_at = NOW()
-            WHERE id = $1
+            FROM quota_definitions
+            WHERE tenant_quotas.quota_definition_id = quota_definitions.id
+            AND tenant_quotas.tenant_id = $1
+            AND quota_definitions.name = $2
             "#,
-            id,
-            status as PaymentStatus,
-            payment_reference,
-            paid_at
+            request.tenant_id,
+            SEAT_QUOTA
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
 
-        Ok(())
+        tx.commit().await?;
+        Ok(assignment)
     }
 
-    pub async fn get_billing_history(&self, tenant_id: Uuid, limit: i64, offset: i64) -> Result<Vec<BillingHistory>> {
-        let records = sqlx::query_as!(
-            BillingHistory,
+    /// Free a user's seat. Returns false when the user held none.
+    pub async fn unassign(&self, request: UnassignSeatRequest) -> Result<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            "DELETE FROM seat_assignments WHERE tenant_id = $1 AND user_id = $2",
+            request.tenant_id,
+            request.user_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query!(
             r#"
-            SELECT 
-                id, tenant_id, license_id, invoice_number, amount, currency, tax_amount,
-                billing_period_start, billing_period_end,
-                payment_status as "payment_status: PaymentStatus",
-                payment_method, payment_reference, paid_at, usage_details,
-                created_at, updated_at
-            FROM billing_history 
+            UPDATE tenant_quotas SET
+                current_usage = GREATEST(current_usage - 1, 0),
+                updated_at = NOW()
+            FROM quota_definitions
+            WHERE tenant_quotas.quota_definition_id = quota_definitions.id
+            AND tenant_quotas.tenant_id = $1
+            AND quota_definitions.name = $2
+            "#,
+            request.tenant_id,
+            SEAT_QUOTA
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    pub async fn list(&self, tenant_id: Uuid) -> Result<Vec<SeatAssignment>> {
+        let assignments = sqlx::query_as!(
+            SeatAssignment,
+            "SELECT * FROM seat_assignments WHERE tenant_id = $1 ORDER BY assigned_at",
+            tenant_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(assignments)
+    }
+
+    pub async fn count(&self, tenant_id: Uuid) -> Result<i64> {
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!" FROM seat_assignments WHERE tenant_id = $1"#,
+            tenant_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    /// Tenants with a seat quota, the ones the nightly reconciliation covers
+    pub async fn get_tenants_with_seats(&self) -> Result<Vec<Uuid>> {
+        let tenant_ids = sqlx::query_scalar!(
+            r#"
+            SELECT tq.tenant_id FROM tenant_quotas tq
+            JOIN quota_definitions qd ON tq.quota_definition_id = qd.id
+            WHERE qd.name = $1
+            ORDER BY tq.tenant_id
+            "#,
+            SEAT_QUOTA
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(tenant_ids)
+    }
+
+    pub async fn record_reconciliation(&self, reconciliation: &SeatReconciliation) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO seat_reconciliations (
+                id, tenant_id, assigned_seats, recorded_usage, billed_seats,
+                usage_corrected, billing_corrected, error, reconciled_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+            reconciliation.id,
+            reconciliation.tenant_id,
+            reconciliation.assigned_seats,
+            reconciliation.recorded_usage,
+            reconciliation.billed_seats,
+            reconciliation.usage_corrected,
+            reconciliation.billing_corrected,
+            reconciliation.error,
+            reconciliation.reconciled_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_reconciliations(&self, tenant_id: Uuid, limit: i64) -> Result<Vec<SeatReconciliation>> {
+        let reconciliations = sqlx::query_as!(
+            SeatReconciliation,
+            r#"
+            SELECT * FROM seat_reconciliations
             WHERE tenant_id = $1
+            ORDER BY reconciled_at DESC
+            LIMIT $2
+            "#,
+            tenant_id,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(reconciliations)
+    }
+}
+
+#[derive(Clone)]
+pub struct DunningRepository {
+    pool: PgPool,
+}
+
+impl DunningRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Open a case for a failed invoice. Stripe reports every failed
+    /// attempt at an invoice, so a case already open for it is returned
+    /// as it is, with `false`.
+    pub async fn open(&self, request: OpenDunningCaseRequest) -> Result<(DunningCase, bool)> {
+        let created = sqlx::query_as!(
+            DunningCase,
+            r#"
+            INSERT INTO dunning_cases (
+                tenant_id, license_id, stripe_invoice_id, amount_due, currency, next_attempt_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (stripe_invoice_id) DO NOTHING
+            RETURNING
+                id, tenant_id, license_id, stripe_invoice_id, amount_due, currency,
+                status as "status: DunningStatus",
+                attempt_count, next_attempt_at, last_error, restricted_features,
+                paused_by, paused_at, resolved_by, resolved_at, resolution_notes,
+                created_at, updated_at
+            "#,
+            request.tenant_id,
+            request.license_id,
+            request.stripe_invoice_id,
+            request.amount_due,
+            request.currency,
+            request.next_attempt_at
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match created {
+            Some(case) => Ok((case, true)),
+            None => {
+                let case = self.get_by_invoice(&request.stripe_invoice_id).await?
+                    .ok_or_else(|| LicenseError::DunningCaseNotFound(request.stripe_invoice_id.clone()))?;
+                Ok((case, false))
+            }
+        }
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<Option<DunningCase>> {
+        let case = sqlx::query_as!(
+            DunningCase,
+            r#"
+            SELECT
+                id, tenant_id, license_id, stripe_invoice_id, amount_due, currency,
+                status as "status: DunningStatus",
+                attempt_count, next_attempt_at, last_error, restricted_features,
+                paused_by, paused_at, resolved_by, resolved_at, resolution_notes,
+                created_at, updated_at
+            FROM dunning_cases
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(case)
+    }
+
+    pub async fn get_by_invoice(&self, stripe_invoice_id: &str) -> Result<Option<DunningCase>> {
+        let case = sqlx::query_as!(
+            DunningCase,
+            r#"
+            SELECT
+                id, tenant_id, license_id, stripe_invoice_id, amount_due, currency,
+                status as "status: DunningStatus",
+                attempt_count, next_attempt_at, last_error, restricted_features,
+                paused_by, paused_at, resolved_by, resolved_at, resolution_notes,
+                created_at, updated_at
+            FROM dunning_cases
+            WHERE stripe_invoice_id = $1
+            "#,
+            stripe_invoice_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(case)
+    }
+
+    pub async fn list(&self, status: Option<DunningStatus>, limit: i64, offset: i64) -> Result<Vec<DunningCase>> {
+        let cases = sqlx::query_as!(
+            DunningCase,
+            r#"
+            SELECT
+                id, tenant_id, license_id, stripe_invoice_id, amount_due, currency,
+                status as "status: DunningStatus",
+                attempt_count, next_attempt_at, last_error, restricted_features,
+                paused_by, paused_at, resolved_by, resolved_at, resolution_notes,
+                created_at, updated_at
+            FROM dunning_cases
+            WHERE $1::dunning_status IS NULL OR status = $1
             ORDER BY created_at DESC
             LIMIT $2 OFFSET $3
             "#,
-            tenant_id,
+            status as Option<DunningStatus>,
             limit,
             offset
         )
         .fetch_all(&self.pool)
         .await?;
 
-        Ok(records)
+        Ok(cases)
+    }
+
+    pub async fn list_for_tenant(&self, tenant_id: Uuid) -> Result<Vec<DunningCase>> {
+        let cases = sqlx::query_as!(
+            DunningCase,
+            r#"
+            SELECT
+                id, tenant_id, license_id, stripe_invoice_id, amount_due, currency,
+                status as "status: DunningStatus",
+                attempt_count, next_attempt_at, last_error, restricted_features,
+                paused_by, paused_at, resolved_by, resolved_at, resolution_notes,
+                created_at, updated_at
+            FROM dunning_cases
+            WHERE tenant_id = $1
+            ORDER BY created_at DESC
+            "#,
+            tenant_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(cases)
+    }
+
+    /// Active cases whose next charge retry is due
+    pub async fn get_due(&self, limit: i64) -> Result<Vec<DunningCase>> {
+        let cases = sqlx::query_as!(
+            DunningCase,
+            r#"
+            SELECT
+                id, tenant_id, license_id, stripe_invoice_id, amount_due, currency,
+                status as "status: DunningStatus",
+                attempt_count, next_attempt_at, last_error, restricted_features,
+                paused_by, paused_at, resolved_by, resolved_at, resolution_notes,
+                created_at, updated_at
+            FROM dunning_cases
+            WHERE status = 'active' AND next_attempt_at <= NOW()
+            ORDER BY next_attempt_at ASC
+            LIMIT $1
+            "#,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(cases)
+    }
+
+    /// Count a failed charge retry and schedule the next one; no next
+    /// attempt leaves the case waiting for suspension
+    pub async fn record_failed_attempt(&self, id: Uuid, error: &str, next_attempt_at: Option<DateTime<Utc>>) -> Result<DunningCase> {
+        let case = sqlx::query_as!(
+            DunningCase,
+            r#"
+            UPDATE dunning_cases
+            SET attempt_count = attempt_count + 1, last_error = $2, next_attempt_at = $3, updated_at = NOW()
+            WHERE id = $1
+            RETURNING
+                id, tenant_id, license_id, stripe_invoice_id, amount_due, currency,
+                status as "status: DunningStatus",
+                attempt_count, next_attempt_at, last_error, restricted_features,
+                paused_by, paused_at, resolved_by, resolved_at, resolution_notes,
+                created_at, updated_at
+            "#,
+            id,
+            error,
+            next_attempt_at
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| LicenseError::DunningCaseNotFound(id.to_string()))?;
+
+        Ok(case)
+    }
+
+    pub async fn set_restricted_features(&self, id: Uuid, features: &[String]) -> Result<()> {
+        sqlx::query!(
+            "UPDATE dunning_cases SET restricted_features = $2, updated_at = NOW() WHERE id = $1",
+            id,
+            features
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Hold an active case's retries and escalation
+    pub async fn pause(&self, id: Uuid, paused_by: Option<Uuid>) -> Result<DunningCase> {
+        let case = sqlx::query_as!(
+            DunningCase,
+            r#"
+            UPDATE dunning_cases
+            SET status = 'paused', paused_by = $2, paused_at = NOW(), updated_at = NOW()
+            WHERE id = $1 AND status = 'active'
+            RETURNING
+                id, tenant_id, license_id, stripe_invoice_id, amount_due, currency,
+                status as "status: DunningStatus",
+                attempt_count, next_attempt_at, last_error, restricted_features,
+                paused_by, paused_at, resolved_by, resolved_at, resolution_notes,
+                created_at, updated_at
+            "#,
+            id,
+            paused_by
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        self.or_wrong_status(id, case, "active").await
+    }
+
+    /// Resume a paused case; a retry that fell due while it was paused
+    /// runs on the next check
+    pub async fn resume(&self, id: Uuid) -> Result<DunningCase> {
+        let case = sqlx::query_as!(
+            DunningCase,
+            r#"
+            UPDATE dunning_cases
+            SET status = 'active', paused_by = NULL, paused_at = NULL, updated_at = NOW()
+            WHERE id = $1 AND status = 'paused'
+            RETURNING
+                id, tenant_id, license_id, stripe_invoice_id, amount_due, currency,
+                status as "status: DunningStatus",
+                attempt_count, next_attempt_at, last_error, restricted_features,
+                paused_by, paused_at, resolved_by, resolved_at, resolution_notes,
+                created_at, updated_at
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        self.or_wrong_status(id, case, "paused").await
+    }
+
+    pub async fn mark_suspended(&self, id: Uuid) -> Result<DunningCase> {
+        let case = sqlx::query_as!(
+            DunningCase,
+            r#"
+            UPDATE dunning_cases
+            SET status = 'suspended', next_attempt_at = NULL, updated_at = NOW()
+            WHERE id = $1 AND status = 'active'
+            RETURNING
+                id, tenant_id, license_id, stripe_invoice_id, amount_due, currency,
+                status as "status: DunningStatus",
+                attempt_count, next_attempt_at, last_error, restricted_features,
+                paused_by, paused_at, resolved_by, resolved_at, resolution_notes,
+                created_at, updated_at
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        self.or_wrong_status(id, case, "active").await
+    }
+
+    /// Close a case as recovered or forgiven. Suspended cases can still be
+    /// closed, which is how a suspended tenant gets back in.
+    pub async fn resolve(&self, id: Uuid, status: DunningStatus, resolved_by: Option<Uuid>, notes: Option<String>) -> Result<DunningCase> {
+        let case = sqlx::query_as!(
+            DunningCase,
+            r#"
+            UPDATE dunning_cases
+            SET status = $2, resolved_by = $3, resolved_at = NOW(), resolution_notes = $4,
+                next_attempt_at = NULL, restricted_features = '{}', updated_at = NOW()
+            WHERE id = $1 AND status IN ('active', 'paused', 'suspended')
+            RETURNING
+                id, tenant_id, license_id, stripe_invoice_id, amount_due, currency,
+                status as "status: DunningStatus",
+                attempt_count, next_attempt_at, last_error, restricted_features,
+                paused_by, paused_at, resolved_by, resolved_at, resolution_notes,
+                created_at, updated_at
+            "#,
+            id,
+            status as DunningStatus,
+            resolved_by,
+            notes
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        self.or_wrong_status(id, case, "open or suspended").await
+    }
+
+    /// Tell a missing case apart from one in the wrong status for an update
+    async fn or_wrong_status(&self, id: Uuid, case: Option<DunningCase>, expected: &str) -> Result<DunningCase> {
+        if let Some(case) = case {
+            return Ok(case);
+        }
+        match self.get(id).await? {
+            Some(case) => Err(LicenseError::ValidationError(format!(
+                "Dunning case {} is {:?}, expected {}",
+                id, case.status, expected
+            ))),
+            None => Err(LicenseError::DunningCaseNotFound(id.to_string())),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct WebhookEventRepository {
+    pool: PgPool,
+}
+
+impl WebhookEventRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Record a delivery. Providers redeliver events, so an event already
+    /// processed or ignored returns `None`; a failed or unfinished one is
+    /// returned again, with its attempts counted, to be retried.
+    pub async fn record(&self, provider: &str, event_id: &str, event_type: &str, payload: &serde_json::Value) -> Result<Option<WebhookEvent>> {
+        let event = sqlx::query_as!(
+            WebhookEvent,
+            r#"
+            INSERT INTO webhook_events (provider, event_id, event_type, payload)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (provider, event_id) DO UPDATE
+                SET attempts = webhook_events.attempts + 1, status = 'received', error = NULL
+                WHERE webhook_events.status IN ('received', 'failed')
+            RETURNING
+                id, provider, event_id, event_type, payload,
+                status as "status: WebhookEventStatus",
+                attempts, error, received_at, processed_at
+            "#,
+            provider,
+            event_id,
+            event_type,
+            payload
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(event)
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<Option<WebhookEvent>> {
+        let event = sqlx::query_as!(
+            WebhookEvent,
+            r#"
+            SELECT
+                id, provider, event_id, event_type, payload,
+                status as "status: WebhookEventStatus",
+                attempts, error, received_at, processed_at
+            FROM webhook_events
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(event)
+    }
+
+    pub async fn list(&self, provider: Option<&str>, status: Option<WebhookEventStatus>, limit: i64, offset: i64) -> Result<Vec<WebhookEvent>> {
+        let events = sqlx::query_as!(
+            WebhookEvent,
+            r#"
+            SELECT
+                id, provider, event_id, event_type, payload,
+                status as "status: WebhookEventStatus",
+                attempts, error, received_at, processed_at
+            FROM webhook_events
+            WHERE ($1::varchar IS NULL OR provider = $1)
+            AND ($2::webhook_event_status IS NULL OR status = $2)
+            ORDER BY received_at DESC
+            LIMIT $3 OFFSET $4
+            "#,
+            provider,
+            status as Option<WebhookEventStatus>,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(events)
+    }
+
+    /// Put an event back to be applied again, for replays
+    pub async fn reopen(&self, id: Uuid) -> Result<WebhookEvent> {
+        let event = sqlx::query_as!(
+            WebhookEvent,
+            r#"
+            UPDATE webhook_events
+            SET status = 'received', attempts = attempts + 1, error = NULL, processed_at = NULL
+            WHERE id = $1
+            RETURNING
+                id, provider, event_id, event_type, payload,
+                status as "status: WebhookEventStatus",
+                attempts, error, received_at, processed_at
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or_else(|| LicenseError::WebhookEventNotFound(id.to_string()))?;
+
+        Ok(event)
+    }
+
+    pub async fn mark(&self, id: Uuid, status: WebhookEventStatus, error: Option<String>) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE webhook_events
+            SET status = $2, error = $3, processed_at = NOW()
+            WHERE id = $1
+            "#,
+            id,
+            status as WebhookEventStatus,
+            error
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+#[derive(Clone)]
+pub struct BillingRepository {
+    pool: PgPool,
+}
+
+impl BillingRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create_billing_record(&self, record: BillingHistory) -> Result<BillingHistory> {
+        let billing_record = sqlx::query_as!(
+            BillingHistory,
+            r#"
+            INSERT INTO billing_history (
+                tenant_id, license_id, invoice_number, amount, currency, tax_amount,
+                billing_period_start, billing_period_end, payment_status,
+                payment_method, payment_reference, usage_details
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            RETURNING 
+                id, tenant_id, license_id, invoice_number, amount, currency, tax_amount,
+                billing_period_start, billing_period_end,
+                payment_status as "payment_status: PaymentStatus",
+                payment_method, payment_reference, paid_at, usage_details,
+                created_at, updated_at
+            "#,
+            record.tenant_id,
+            record.license_id,
+            record.invoice_number,
+            record.amount,
+            record.currency,
+            record.tax_amount,
+            record.billing_period_start,
+            record.billing_period_end,
+            record.payment_status as PaymentStatus,
+            record.payment_method,
+            record.payment_reference,
+            record.usage_details
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(billing_record)
+    }
+
+    pub async fn update_payment_status(&self, id: Uuid, status: PaymentStatus, payment_reference: Option<String>) -> Result<()> {
+        let paid_at = if matches!(status, PaymentStatus::Completed) {
+            Some(Utc::now())
+        } else {
+            None
+        };
+
+        sqlx::query!(
+            r#"
+            UPDATE billing_history SET
+                payment_status = $2,
+                payment_reference = COALESCE($3, payment_reference),
+                paid_at = $4,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+            id,
+            status as PaymentStatus,
+            payment_reference,
+            paid_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_by_id(&self, id: Uuid) -> Result<Option<BillingHistory>> {
+        let record = sqlx::query_as!(
+            BillingHistory,
+            r#"
+            SELECT
+                id, tenant_id, license_id, invoice_number, amount, currency, tax_amount,
+                billing_period_start, billing_period_end,
+                payment_status as "payment_status: PaymentStatus",
+                payment_method, payment_reference, paid_at, usage_details,
+                created_at, updated_at
+            FROM billing_history
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    /// The record of a provider's invoice, charge or sale
+    pub async fn get_by_payment_reference(&self, payment_reference: &str) -> Result<Option<BillingHistory>> {
+        let record = sqlx::query_as!(
+            BillingHistory,
+            r#"
+            SELECT 
+                id, tenant_id, license_id, invoice_number, amount, currency, tax_amount,
+                billing_period_start, billing_period_end,
+                payment_status as "payment_status: PaymentStatus",
+                payment_method, payment_reference, paid_at, usage_details,
+                created_at, updated_at
+            FROM billing_history 
+            WHERE payment_reference = $1
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+            payment_reference
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(record)
+    }
+
+    pub async fn get_billing_history(&self, tenant_id: Uuid, limit: i64, offset: i64) -> Result<Vec<BillingHistory>> {
+        let records = sqlx::query_as!(
+            BillingHistory,
+            r#"
+            SELECT 
+                id, tenant_id, license_id, invoice_number, amount, currency, tax_amount,
+                billing_period_start, billing_period_end,
+                payment_status as "payment_status: PaymentStatus",
+                payment_method, payment_reference, paid_at, usage_details,
+                created_at, updated_at
+            FROM billing_history 
+            WHERE tenant_id = $1
+            ORDER BY created_at DESC
+            LIMIT $2 OFFSET $3
+            "#,
+            tenant_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+
+    /// Store module usage charges. Charges for an instance, hour and metric
+    /// that are already stored are skipped, so reports can be retried.
+    pub async fn record_module_usage_charges(&self, charges: &[ModuleUsageCharge]) -> Result<u64> {
+        let mut tx = self.pool.begin().await?;
+        let mut recorded = 0;
+
+        for charge in charges {
+            let result = sqlx::query!(
+                r#"
+                INSERT INTO module_usage_charges (
+                    id, tenant_id, module_id, instance_id, developer_id,
+                    period_start, period_end, metric, quantity, unit_price,
+                    amount, developer_amount, platform_amount, currency
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+                ON CONFLICT (instance_id, period_start, metric) DO NOTHING
+                "#,
+                charge.id,
+                charge.tenant_id,
+                charge.module_id,
+                charge.instance_id,
+                charge.developer_id,
+                charge.period_start,
+                charge.period_end,
+                charge.metric,
+                charge.quantity,
+                charge.unit_price,
+                charge.amount,
+                charge.developer_amount,
+                charge.platform_amount,
+                charge.currency
+            )
+            .execute(&mut *tx)
+            .await?;
+            recorded += result.rows_affected();
+        }
+
+        tx.commit().await?;
+        Ok(recorded)
+    }
+
+    pub async fn get_uninvoiced_module_usage(&self, tenant_id: Uuid) -> Result<Vec<ModuleUsageCharge>> {
+        let charges = sqlx::query_as!(
+            ModuleUsageCharge,
+            r#"
+            SELECT * FROM module_usage_charges
+            WHERE tenant_id = $1 AND invoice_number IS NULL
+            ORDER BY period_start
+            "#,
+            tenant_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(charges)
+    }
+
+    pub async fn mark_module_usage_invoiced(&self, charge_ids: &[Uuid], invoice_number: &str) -> Result<()> {
+        sqlx::query!(
+            r#"
+            UPDATE module_usage_charges SET invoice_number = $2
+            WHERE id = ANY($1) AND invoice_number IS NULL
+            "#,
+            charge_ids,
+            invoice_number
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_developer_revenue(&self, developer_id: &str, start_date: DateTime<Utc>, end_date: DateTime<Utc>) -> Result<Vec<ModuleRevenue>> {
+        let revenue = sqlx::query_as!(
+            ModuleRevenue,
+            r#"
+            SELECT 
+                module_id, currency,
+                SUM(amount) as "amount!",
+                SUM(developer_amount) as "developer_amount!",
+                SUM(platform_amount) as "platform_amount!"
+            FROM module_usage_charges
+            WHERE developer_id = $1
+            AND period_start >= $2
+            AND period_start < $3
+            GROUP BY module_id, currency
+            ORDER BY module_id, currency
+            "#,
+            developer_id,
+            start_date,
+            end_date
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(revenue)
+    }
+
+    /// Invoiced amounts, including tax, per tenant and currency for invoices
+    /// whose billing period starts in the range
+    pub async fn get_billing_totals(&self, tenant_ids: &[Uuid], start_date: DateTime<Utc>, end_date: DateTime<Utc>) -> Result<Vec<TenantBillingTotal>> {
+        let totals = sqlx::query_as!(
+            TenantBillingTotal,
+            r#"
+            SELECT 
+                tenant_id, currency,
+                COALESCE(SUM(amount + tax_amount) FILTER (WHERE payment_status NOT IN ('cancelled', 'refunded')), 0) as "billed!",
+                COALESCE(SUM(amount + tax_amount) FILTER (WHERE payment_status = 'completed'), 0) as "paid!",
+                COALESCE(SUM(amount + tax_amount) FILTER (WHERE payment_status IN ('pending', 'failed')), 0) as "outstanding!"
+            FROM billing_history
+            WHERE tenant_id = ANY($1)
+            AND billing_period_start >= $2
+            AND billing_period_start < $3
+            GROUP BY tenant_id, currency
+            ORDER BY tenant_id, currency
+            "#,
+            tenant_ids,
+            start_date,
+            end_date
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(totals)
+    }
+}
+
+#[derive(Clone)]
+pub struct BillingDocumentRepository {
+    pool: PgPool,
+}
+
+impl BillingDocumentRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    /// Record a generated document. A document generated concurrently for
+    /// the same record wins, and is returned instead.
+    pub async fn create(&self, document: BillingDocument) -> Result<BillingDocument> {
+        let created = sqlx::query_as!(
+            BillingDocument,
+            r#"
+            INSERT INTO billing_documents (
+                tenant_id, billing_history_id, document_type, document_number,
+                file_id, filename, file_size, amount, currency
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (billing_history_id, document_type) DO UPDATE SET
+                document_number = billing_documents.document_number
+            RETURNING
+                id, tenant_id, billing_history_id,
+                document_type as "document_type: BillingDocumentType",
+                document_number, file_id, filename, file_size, amount, currency, issued_at
+            "#,
+            document.tenant_id,
+            document.billing_history_id,
+            document.document_type as BillingDocumentType,
+            document.document_number,
+            document.file_id,
+            document.filename,
+            document.file_size,
+            document.amount,
+            document.currency
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(created)
+    }
+
+    pub async fn get(&self, id: Uuid) -> Result<Option<BillingDocument>> {
+        let document = sqlx::query_as!(
+            BillingDocument,
+            r#"
+            SELECT
+                id, tenant_id, billing_history_id,
+                document_type as "document_type: BillingDocumentType",
+                document_number, file_id, filename, file_size, amount, currency, issued_at
+            FROM billing_documents
+            WHERE id = $1
+            "#,
+            id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(document)
+    }
+
+    pub async fn list_for_tenant(&self, tenant_id: Uuid, limit: i64, offset: i64) -> Result<Vec<BillingDocument>> {
+        let documents = sqlx::query_as!(
+            BillingDocument,
+            r#"
+            SELECT
+                id, tenant_id, billing_history_id,
+                document_type as "document_type: BillingDocumentType",
+                document_number, file_id, filename, file_size, amount, currency, issued_at
+            FROM billing_documents
+            WHERE tenant_id = $1
+            ORDER BY issued_at DESC
+            LIMIT $2 OFFSET $3
+            "#,
+            tenant_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(documents)
+    }
+
+    pub async fn list_for_record(&self, billing_history_id: Uuid) -> Result<Vec<BillingDocument>> {
+        let documents = sqlx::query_as!(
+            BillingDocument,
+            r#"
+            SELECT
+                id, tenant_id, billing_history_id,
+                document_type as "document_type: BillingDocumentType",
+                document_number, file_id, filename, file_size, amount, currency, issued_at
+            FROM billing_documents
+            WHERE billing_history_id = $1
+            ORDER BY document_type
+            "#,
+            billing_history_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(documents)
+    }
+
+    /// Billing records without an invoice, or paid without a receipt
+    pub async fn get_records_missing_documents(&self, limit: i64) -> Result<Vec<BillingHistory>> {
+        let records = sqlx::query_as!(
+            BillingHistory,
+            r#"
+            SELECT
+                bh.id, bh.tenant_id, bh.license_id, bh.invoice_number, bh.amount, bh.currency, bh.tax_amount,
+                bh.billing_period_start, bh.billing_period_end,
+                bh.payment_status as "payment_status: PaymentStatus",
+                bh.payment_method, bh.payment_reference, bh.paid_at, bh.usage_details,
+                bh.created_at, bh.updated_at
+            FROM billing_history bh
+            WHERE NOT EXISTS (
+                SELECT 1 FROM billing_documents bd
+                WHERE bd.billing_history_id = bh.id AND bd.document_type = 'invoice'
+            )
+            OR (
+                bh.payment_status = 'completed'
+                AND NOT EXISTS (
+                    SELECT 1 FROM billing_documents bd
+                    WHERE bd.billing_history_id = bh.id AND bd.document_type = 'receipt'
+                )
+            )
+            ORDER BY bh.created_at
+            LIMIT $1
+            "#,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(records)
+    }
+}
+
+#[derive(Clone)]
+pub struct PromotionRepository {
+    pool: PgPool,
+}
+
+impl PromotionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // Coupons
+    pub async fn create_coupon(&self, code: &str, request: &CreateCouponRequest, eligible_tiers: &[String]) -> Result<Coupon> {
+        let coupon = sqlx::query_as!(
+            Coupon,
+            r#"
+            INSERT INTO coupons (
+                code, description, discount_type, discount_value, currency, eligible_tiers,
+                max_redemptions, max_redemptions_per_tenant, valid_from, valid_until, created_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, COALESCE($8, 1), COALESCE($9, NOW()), $10, $11)
+            RETURNING
+                id, code, description,
+                discount_type as "discount_type: CouponDiscountType",
+                discount_value, currency, eligible_tiers,
+                max_redemptions, max_redemptions_per_tenant, redemption_count,
+                valid_from, valid_until, active, created_by, created_at, updated_at
+            "#,
+            code,
+            request.description,
+            request.discount_type as CouponDiscountType,
+            request.discount_value,
+            request.currency,
+            eligible_tiers,
+            request.max_redemptions,
+            request.max_redemptions_per_tenant,
+            request.valid_from,
+            request.valid_until,
+            request.created_by
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(coupon)
+    }
+
+    pub async fn get_coupon_by_code(&self, code: &str) -> Result<Option<Coupon>> {
+        let coupon = sqlx::query_as!(
+            Coupon,
+            r#"
+            SELECT
+                id, code, description,
+                discount_type as "discount_type: CouponDiscountType",
+                discount_value, currency, eligible_tiers,
+                max_redemptions, max_redemptions_per_tenant, redemption_count,
+                valid_from, valid_until, active, created_by, created_at, updated_at
+            FROM coupons
+            WHERE code = $1
+            "#,
+            code
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(coupon)
+    }
+
+    pub async fn list_coupons(&self, active_only: bool, limit: i64, offset: i64) -> Result<Vec<Coupon>> {
+        let coupons = sqlx::query_as!(
+            Coupon,
+            r#"
+            SELECT
+                id, code, description,
+                discount_type as "discount_type: CouponDiscountType",
+                discount_value, currency, eligible_tiers,
+                max_redemptions, max_redemptions_per_tenant, redemption_count,
+                valid_from, valid_until, active, created_by, created_at, updated_at
+            FROM coupons
+            WHERE NOT $1 OR active
+            ORDER BY created_at DESC
+            LIMIT $2 OFFSET $3
+            "#,
+            active_only,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(coupons)
+    }
+
+    pub async fn update_coupon(&self, code: &str, request: &UpdateCouponRequest) -> Result<Option<Coupon>> {
+        let coupon = sqlx::query_as!(
+            Coupon,
+            r#"
+            UPDATE coupons SET
+                description = COALESCE($2, description),
+                max_redemptions = COALESCE($3, max_redemptions),
+                valid_until = COALESCE($4, valid_until),
+                active = COALESCE($5, active),
+                updated_at = NOW()
+            WHERE code = $1
+            RETURNING
+                id, code, description,
+                discount_type as "discount_type: CouponDiscountType",
+                discount_value, currency, eligible_tiers,
+                max_redemptions, max_redemptions_per_tenant, redemption_count,
+                valid_from, valid_until, active, created_by, created_at, updated_at
+            "#,
+            code,
+            request.description,
+            request.max_redemptions,
+            request.valid_until,
+            request.active
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(coupon)
+    }
+
+    pub async fn count_tenant_redemptions(&self, coupon_id: Uuid, tenant_id: Uuid) -> Result<i64> {
+        let count = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!" FROM coupon_redemptions WHERE coupon_id = $1 AND tenant_id = $2"#,
+            coupon_id,
+            tenant_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count)
+    }
+
+    pub async fn list_redemptions(&self, coupon_id: Uuid, limit: i64, offset: i64) -> Result<Vec<CouponRedemption>> {
+        let redemptions = sqlx::query_as!(
+            CouponRedemption,
+            r#"
+            SELECT * FROM coupon_redemptions
+            WHERE coupon_id = $1
+            ORDER BY redeemed_at DESC
+            LIMIT $2 OFFSET $3
+            "#,
+            coupon_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(redemptions)
+    }
+
+    /// Record a use of the coupon, unless that would take it over either of
+    /// its redemption limits or it stopped being valid in the meantime
+    pub async fn redeem(
+        &self,
+        coupon_id: Uuid,
+        tenant_id: Uuid,
+        license_id: Option<Uuid>,
+        original_amount: Decimal,
+        discount_amount: Decimal,
+        currency: &str,
+    ) -> Result<Option<CouponRedemption>> {
+        let mut tx = self.pool.begin().await?;
+
+        // Taking the count locks the coupon, so concurrent redemptions
+        // cannot both take the last one
+        let coupon = sqlx::query!(
+            r#"
+            UPDATE coupons SET
+                redemption_count = redemption_count + 1,
+                updated_at = NOW()
+            WHERE id = $1
+            AND active
+            AND valid_from <= NOW()
+            AND (valid_until IS NULL OR valid_until > NOW())
+            AND (max_redemptions IS NULL OR redemption_count < max_redemptions)
+            RETURNING max_redemptions_per_tenant
+            "#,
+            coupon_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(coupon) = coupon else {
+            return Ok(None);
+        };
+
+        let tenant_redemptions = sqlx::query_scalar!(
+            r#"SELECT COUNT(*) as "count!" FROM coupon_redemptions WHERE coupon_id = $1 AND tenant_id = $2"#,
+            coupon_id,
+            tenant_id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if tenant_redemptions >= coupon.max_redemptions_per_tenant as i64 {
+            return Ok(None);
+        }
+
+        let redemption = sqlx::query_as!(
+            CouponRedemption,
+            r#"
+            INSERT INTO coupon_redemptions (coupon_id, tenant_id, license_id, original_amount, discount_amount, currency)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING *
+            "#,
+            coupon_id,
+            tenant_id,
+            license_id,
+            original_amount,
+            discount_amount,
+            currency
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(redemption))
+    }
+
+    /// Give a redemption back, e.g. when the charge it discounted failed.
+    /// Returns the redemption, or None when it was already given back.
+    pub async fn revert_redemption(&self, redemption_id: Uuid) -> Result<Option<CouponRedemption>> {
+        let mut tx = self.pool.begin().await?;
+
+        let redemption = sqlx::query_as!(
+            CouponRedemption,
+            "DELETE FROM coupon_redemptions WHERE id = $1 RETURNING *",
+            redemption_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        let Some(redemption) = redemption else {
+            return Ok(None);
+        };
+
+        sqlx::query!(
+            r#"
+            UPDATE coupons SET
+                redemption_count = GREATEST(redemption_count - 1, 0),
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+            redemption.coupon_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(redemption))
+    }
+
+    // Tenant credit ledger
+    pub async fn credit_balances(&self, tenant_id: Uuid) -> Result<Vec<CreditBalance>> {
+        let balances = sqlx::query_as!(
+            CreditBalance,
+            r#"
+            SELECT currency, SUM(amount) as "balance!"
+            FROM tenant_credit_entries
+            WHERE tenant_id = $1
+            GROUP BY currency
+            ORDER BY currency
+            "#,
+            tenant_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(balances)
+    }
+
+    pub async fn credit_balance(&self, tenant_id: Uuid, currency: &str) -> Result<Decimal> {
+        let balance = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(SUM(amount), 0) as "balance!"
+            FROM tenant_credit_entries
+            WHERE tenant_id = $1 AND currency = $2
+            "#,
+            tenant_id,
+            currency
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(balance)
+    }
+
+    pub async fn list_credit_entries(&self, tenant_id: Uuid, limit: i64, offset: i64) -> Result<Vec<CreditEntry>> {
+        let entries = sqlx::query_as!(
+            CreditEntry,
+            r#"
+            SELECT
+                id, tenant_id,
+                entry_type as "entry_type: CreditEntryType",
+                amount, currency, reason, reference, created_by, created_at
+            FROM tenant_credit_entries
+            WHERE tenant_id = $1
+            ORDER BY created_at DESC
+            LIMIT $2 OFFSET $3
+            "#,
+            tenant_id,
+            limit,
+            offset
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    pub async fn grant_credit(&self, request: &GrantCreditRequest) -> Result<CreditEntry> {
+        let entry = sqlx::query_as!(
+            CreditEntry,
+            r#"
+            INSERT INTO tenant_credit_entries (tenant_id, entry_type, amount, currency, reason, created_by)
+            VALUES ($1, 'grant', $2, $3, $4, $5)
+            RETURNING
+                id, tenant_id,
+                entry_type as "entry_type: CreditEntryType",
+                amount, currency, reason, reference, created_by, created_at
+            "#,
+            request.tenant_id,
+            request.amount,
+            request.currency,
+            request.reason,
+            request.granted_by
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(entry)
+    }
+
+    /// Grant credit for `reference` once; granting it again returns the
+    /// entry the first grant created
+    pub async fn grant_credit_once(&self, request: &GrantCreditRequest, reference: &str) -> Result<CreditEntry> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!("SELECT pg_advisory_xact_lock(hashtext($1::text))", request.tenant_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+
+        let existing = sqlx::query_as!(
+            CreditEntry,
+            r#"
+            SELECT
+                id, tenant_id,
+                entry_type as "entry_type: CreditEntryType",
+                amount, currency, reason, reference, created_by, created_at
+            FROM tenant_credit_entries
+            WHERE tenant_id = $1 AND entry_type = 'grant' AND reference = $2
+            "#,
+            request.tenant_id,
+            reference
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        if let Some(entry) = existing {
+            return Ok(entry);
+        }
+
+        let entry = sqlx::query_as!(
+            CreditEntry,
+            r#"
+            INSERT INTO tenant_credit_entries (tenant_id, entry_type, amount, currency, reason, reference, created_by)
+            VALUES ($1, 'grant', $2, $3, $4, $5, $6)
+            RETURNING
+                id, tenant_id,
+                entry_type as "entry_type: CreditEntryType",
+                amount, currency, reason, reference, created_by, created_at
+            "#,
+            request.tenant_id,
+            request.amount,
+            request.currency,
+            request.reason,
+            reference,
+            request.granted_by
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(entry)
+    }
+
+    /// Use up to `amount` of the tenant's credit towards a charge. Returns
+    /// the ledger entry, or None when the tenant has no credit.
+    pub async fn apply_credit(
+        &self,
+        tenant_id: Uuid,
+        amount: Decimal,
+        currency: &str,
+        reference: Option<&str>,
+    ) -> Result<Option<CreditEntry>> {
+        let mut tx = self.pool.begin().await?;
+
+        // Serialize use of the tenant's credit so it cannot be spent twice
+        sqlx::query!("SELECT pg_advisory_xact_lock(hashtext($1::text))", tenant_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+
+        let balance = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(SUM(amount), 0) as "balance!"
+            FROM tenant_credit_entries
+            WHERE tenant_id = $1 AND currency = $2
+            "#,
+            tenant_id,
+            currency
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let applied = balance.min(amount);
+        if applied <= Decimal::ZERO {
+            return Ok(None);
+        }
+
+        let entry = sqlx::query_as!(
+            CreditEntry,
+            r#"
+            INSERT INTO tenant_credit_entries (tenant_id, entry_type, amount, currency, reason, reference)
+            VALUES ($1, 'applied', $2, $3, 'Applied to a charge', $4)
+            RETURNING
+                id, tenant_id,
+                entry_type as "entry_type: CreditEntryType",
+                amount, currency, reason, reference, created_by, created_at
+            "#,
+            tenant_id,
+            -applied,
+            currency,
+            reference
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(entry))
+    }
+
+    /// Return credit used by an entry, once. Returns the reversal, or None
+    /// when the entry was already reversed.
+    pub async fn reverse_credit(&self, entry: &CreditEntry, reason: &str) -> Result<Option<CreditEntry>> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!("SELECT pg_advisory_xact_lock(hashtext($1::text))", entry.tenant_id.to_string())
+            .execute(&mut *tx)
+            .await?;
+
+        let reversed = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM tenant_credit_entries
+                WHERE entry_type = 'reversal' AND reference = $1
+            ) as "reversed!"
+            "#,
+            entry.id.to_string()
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if reversed {
+            return Ok(None);
+        }
+
+        let reversal = sqlx::query_as!(
+            CreditEntry,
+            r#"
+            INSERT INTO tenant_credit_entries (tenant_id, entry_type, amount, currency, reason, reference)
+            VALUES ($1, 'reversal', $2, $3, $4, $5)
+            RETURNING
+                id, tenant_id,
+                entry_type as "entry_type: CreditEntryType",
+                amount, currency, reason, reference, created_by, created_at
+            "#,
+            entry.tenant_id,
+            -entry.amount,
+            entry.currency,
+            reason,
+            entry.id.to_string()
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(reversal))
+    }
+}
+
+#[derive(Clone)]
+pub struct TaxRepository {
+    pool: PgPool,
+}
+
+impl TaxRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // Tenant tax profiles
+    pub async fn get_profile(&self, tenant_id: Uuid) -> Result<Option<TenantTaxProfile>> {
+        let profile = sqlx::query_as!(
+            TenantTaxProfile,
+            r#"
+            SELECT
+                tenant_id, legal_name, country, region, postal_code, tax_id,
+                tax_id_status as "tax_id_status: TaxIdStatus",
+                tax_id_checked_at, tax_exempt, created_at, updated_at
+            FROM tenant_tax_profiles
+            WHERE tenant_id = $1
+            "#,
+            tenant_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(profile)
+    }
+
+    /// Create or replace the tenant's profile. A changed tax ID is unverified
+    /// until it is checked again.
+    pub async fn upsert_profile(
+        &self,
+        tenant_id: Uuid,
+        request: &UpsertTaxProfileRequest,
+        tax_id: Option<&str>,
+    ) -> Result<TenantTaxProfile> {
+        let profile = sqlx::query_as!(
+            TenantTaxProfile,
+            r#"
+            INSERT INTO tenant_tax_profiles (
+                tenant_id, legal_name, country, region, postal_code, tax_id, tax_exempt
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, COALESCE($7, FALSE))
+            ON CONFLICT (tenant_id) DO UPDATE SET
+                legal_name = EXCLUDED.legal_name,
+                country = EXCLUDED.country,
+                region = EXCLUDED.region,
+                postal_code = EXCLUDED.postal_code,
+                tax_id = EXCLUDED.tax_id,
+                tax_id_status = CASE
+                    WHEN tenant_tax_profiles.tax_id IS NOT DISTINCT FROM EXCLUDED.tax_id
+                        THEN tenant_tax_profiles.tax_id_status
+                    ELSE 'unverified'
+                END,
+                tax_id_checked_at = CASE
+                    WHEN tenant_tax_profiles.tax_id IS NOT DISTINCT FROM EXCLUDED.tax_id
+                        THEN tenant_tax_profiles.tax_id_checked_at
+                    ELSE NULL
+                END,
+                tax_exempt = COALESCE($7, tenant_tax_profiles.tax_exempt),
+                updated_at = NOW()
+            RETURNING
+                tenant_id, legal_name, country, region, postal_code, tax_id,
+                tax_id_status as "tax_id_status: TaxIdStatus",
+                tax_id_checked_at, tax_exempt, created_at, updated_at
+            "#,
+            tenant_id,
+            request.legal_name,
+            request.country,
+            request.region,
+            request.postal_code,
+            tax_id,
+            request.tax_exempt
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(profile)
+    }
+
+    /// Record the outcome of a check of the tax ID, unless it has since changed
+    pub async fn set_tax_id_status(&self, tenant_id: Uuid, tax_id: &str, status: TaxIdStatus) -> Result<Option<TenantTaxProfile>> {
+        let profile = sqlx::query_as!(
+            TenantTaxProfile,
+            r#"
+            UPDATE tenant_tax_profiles SET
+                tax_id_status = $3,
+                tax_id_checked_at = NOW(),
+                updated_at = NOW()
+            WHERE tenant_id = $1 AND tax_id = $2
+            RETURNING
+                tenant_id, legal_name, country, region, postal_code, tax_id,
+                tax_id_status as "tax_id_status: TaxIdStatus",
+                tax_id_checked_at, tax_exempt, created_at, updated_at
+            "#,
+            tenant_id,
+            tax_id,
+            status as TaxIdStatus
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(profile)
+    }
+
+    // Manual tax tables
+    pub async fn list_rates(&self, country: Option<&str>) -> Result<Vec<TaxRate>> {
+        let rates = sqlx::query_as!(
+            TaxRate,
+            r#"
+            SELECT id, country, region, tax_type, name, rate, active, created_at, updated_at
+            FROM tax_rates
+            WHERE active AND ($1::text IS NULL OR country = $1)
+            ORDER BY country, region NULLS FIRST, tax_type
+            "#,
+            country
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rates)
+    }
+
+    /// Rates of the country, and of the region within it
+    pub async fn get_applicable_rates(&self, country: &str, region: Option<&str>) -> Result<Vec<TaxRate>> {
+        let rates = sqlx::query_as!(
+            TaxRate,
+            r#"
+            SELECT id, country, region, tax_type, name, rate, active, created_at, updated_at
+            FROM tax_rates
+            WHERE active AND country = $1 AND (region IS NULL OR region = $2)
+            ORDER BY region NULLS FIRST, tax_type
+            "#,
+            country,
+            region
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rates)
+    }
+
+    /// Add a rate, retiring the active rate of the same jurisdiction and type
+    pub async fn replace_rate(&self, request: &CreateTaxRateRequest) -> Result<TaxRate> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!(
+            r#"
+            UPDATE tax_rates SET active = FALSE, updated_at = NOW()
+            WHERE active AND country = $1 AND region IS NOT DISTINCT FROM $2 AND tax_type = $3
+            "#,
+            request.country,
+            request.region,
+            request.tax_type
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let rate = sqlx::query_as!(
+            TaxRate,
+            r#"
+            INSERT INTO tax_rates (country, region, tax_type, name, rate)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, country, region, tax_type, name, rate, active, created_at, updated_at
+            "#,
+            request.country,
+            request.region,
+            request.tax_type,
+            request.name,
+            request.rate
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(rate)
+    }
+
+    // Invoice tax lines
+    pub async fn record_invoice_tax_lines(&self, lines: &[InvoiceTaxLine]) -> Result<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for line in lines {
+            sqlx::query!(
+                r#"
+                INSERT INTO invoice_tax_lines (
+                    id, invoice_number, tenant_id, line_index, description, taxable_amount,
+                    tax_type, jurisdiction, rate, tax_amount, currency, provider, reverse_charge
+                )
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+                "#,
+                line.id,
+                line.invoice_number,
+                line.tenant_id,
+                line.line_index,
+                line.description,
+                line.taxable_amount,
+                line.tax_type,
+                line.jurisdiction,
+                line.rate,
+                line.tax_amount,
+                line.currency,
+                line.provider,
+                line.reverse_charge
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_invoice_tax_lines(&self, tenant_id: Uuid, invoice_number: &str) -> Result<Vec<InvoiceTaxLine>> {
+        let lines = sqlx::query_as!(
+            InvoiceTaxLine,
+            r#"
+            SELECT
+                id, invoice_number, tenant_id, line_index, description, taxable_amount,
+                tax_type, jurisdiction, rate, tax_amount, currency, provider, reverse_charge, created_at
+            FROM invoice_tax_lines
+            WHERE tenant_id = $1 AND invoice_number = $2
+            ORDER BY line_index, jurisdiction
+            "#,
+            tenant_id,
+            invoice_number
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(lines)
+    }
+}
+
+#[derive(Clone)]
+pub struct PricingRepository {
+    pool: PgPool,
+}
+
+impl PricingRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+
+    // Price books
+    pub async fn get_entry(&self, currency: &str, tier: &SubscriptionTier, cycle: &BillingCycle) -> Result<Option<PriceBookEntry>> {
+        let entry = sqlx::query_as!(
+            PriceBookEntry,
+            r#"
+            SELECT
+                id, currency,
+                subscription_tier as "subscription_tier: SubscriptionTier",
+                billing_cycle as "billing_cycle: BillingCycle",
+                price, setup_fee, stripe_price_id, created_at, updated_at
+            FROM price_book_entries
+            WHERE currency = $1 AND subscription_tier = $2 AND billing_cycle = $3
+            "#,
+            currency,
+            tier.clone() as SubscriptionTier,
+            cycle.clone() as BillingCycle
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(entry)
+    }
+
+    pub async fn list_entries(&self, currency: Option<&str>) -> Result<Vec<PriceBookEntry>> {
+        let entries = sqlx::query_as!(
+            PriceBookEntry,
+            r#"
+            SELECT
+                id, currency,
+                subscription_tier as "subscription_tier: SubscriptionTier",
+                billing_cycle as "billing_cycle: BillingCycle",
+                price, setup_fee, stripe_price_id, created_at, updated_at
+            FROM price_book_entries
+            WHERE $1::text IS NULL OR currency = $1
+            ORDER BY currency, subscription_tier, billing_cycle
+            "#,
+            currency
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+
+    pub async fn upsert_entry(&self, request: &UpsertPriceBookEntryRequest) -> Result<PriceBookEntry> {
+        let entry = sqlx::query_as!(
+            PriceBookEntry,
+            r#"
+            INSERT INTO price_book_entries (currency, subscription_tier, billing_cycle, price, setup_fee, stripe_price_id)
+            VALUES ($1, $2, $3, $4, COALESCE($5, 0), $6)
+            ON CONFLICT (currency, subscription_tier, billing_cycle) DO UPDATE SET
+                price = EXCLUDED.price,
+                setup_fee = COALESCE($5, price_book_entries.setup_fee),
+                stripe_price_id = COALESCE($6, price_book_entries.stripe_price_id),
+                updated_at = NOW()
+            RETURNING
+                id, currency,
+                subscription_tier as "subscription_tier: SubscriptionTier",
+                billing_cycle as "billing_cycle: BillingCycle",
+                price, setup_fee, stripe_price_id, created_at, updated_at
+            "#,
+            request.currency,
+            request.subscription_tier.clone() as SubscriptionTier,
+            request.billing_cycle.clone() as BillingCycle,
+            request.price,
+            request.setup_fee,
+            request.stripe_price_id
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(entry)
+    }
+
+    // Exchange rates
+    pub async fn latest_rate(&self, base_currency: &str, quote_currency: &str) -> Result<Option<ExchangeRate>> {
+        let rate = sqlx::query_as!(
+            ExchangeRate,
+            r#"
+            SELECT id, base_currency, quote_currency, rate, source, effective_at, created_at
+            FROM exchange_rates
+            WHERE base_currency = $1 AND quote_currency = $2
+            ORDER BY effective_at DESC
+            LIMIT 1
+            "#,
+            base_currency,
+            quote_currency
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(rate)
+    }
+
+    /// The latest rate of each currency against the base currency
+    pub async fn latest_rates(&self, base_currency: &str) -> Result<Vec<ExchangeRate>> {
+        let rates = sqlx::query_as!(
+            ExchangeRate,
+            r#"
+            SELECT DISTINCT ON (quote_currency)
+                id, base_currency, quote_currency, rate, source, effective_at, created_at
+            FROM exchange_rates
+            WHERE base_currency = $1
+            ORDER BY quote_currency, effective_at DESC
+            "#,
+            base_currency
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rates)
+    }
+
+    pub async fn record_rate(
+        &self,
+        base_currency: &str,
+        quote_currency: &str,
+        rate: Decimal,
+        source: &str,
+        effective_at: DateTime<Utc>,
+    ) -> Result<ExchangeRate> {
+        let rate = sqlx::query_as!(
+            ExchangeRate,
+            r#"
+            INSERT INTO exchange_rates (base_currency, quote_currency, rate, source, effective_at)
+            VALUES ($1, $2, $3, $4, $5)
+            RETURNING id, base_currency, quote_currency, rate, source, effective_at, created_at
+            "#,
+            base_currency,
+            quote_currency,
+            rate,
+            source,
+            effective_at
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(rate)
+    }
+
+    // Tenant currencies
+    pub async fn get_tenant_currency(&self, tenant_id: Uuid) -> Result<Option<TenantBillingCurrency>> {
+        let currency = sqlx::query_as!(
+            TenantBillingCurrency,
+            r#"
+            SELECT tenant_id, currency, created_at, updated_at
+            FROM tenant_billing_currencies
+            WHERE tenant_id = $1
+            "#,
+            tenant_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(currency)
+    }
+
+    pub async fn set_tenant_currency(&self, tenant_id: Uuid, currency: &str) -> Result<TenantBillingCurrency> {
+        let currency = sqlx::query_as!(
+            TenantBillingCurrency,
+            r#"
+            INSERT INTO tenant_billing_currencies (tenant_id, currency)
+            VALUES ($1, $2)
+            ON CONFLICT (tenant_id) DO UPDATE SET
+                currency = EXCLUDED.currency,
+                updated_at = NOW()
+            RETURNING tenant_id, currency, created_at, updated_at
+            "#,
+            tenant_id,
+            currency
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(currency)
+    }
+
+    // Invoice exchange rate snapshots
+    pub async fn record_invoice_exchange_rate(&self, snapshot: &InvoiceExchangeRate) -> Result<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO invoice_exchange_rates (
+                invoice_number, tenant_id, base_currency, invoice_currency,
+                rate, source, rate_effective_at, base_amount
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            snapshot.invoice_number,
+            snapshot.tenant_id,
+            snapshot.base_currency,
+            snapshot.invoice_currency,
+            snapshot.rate,
+            snapshot.source,
+            snapshot.rate_effective_at,
+            snapshot.base_amount
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_invoice_exchange_rate(&self, tenant_id: Uuid, invoice_number: &str) -> Result<Option<InvoiceExchangeRate>> {
+        let snapshot = sqlx::query_as!(
+            InvoiceExchangeRate,
+            r#"
+            SELECT
+                invoice_number, tenant_id, base_currency, invoice_currency,
+                rate, source, rate_effective_at, base_amount, created_at
+            FROM invoice_exchange_rates
+            WHERE tenant_id = $1 AND invoice_number = $2
+            "#,
+            tenant_id,
+            invoice_number
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(snapshot)
     }
 }
 
@@ -4,20 +4,49 @@ use uuid::Uuid;
 
 use crate::{
     activities::*,
-    billing::BillingService,
+    billing::{BillingService, PayPalWebhookHeaders},
+    branding::BrandingClient,
+    config::{CurrencyConfig, DunningConfig, GraceConfig},
+    documents,
     error::{LicenseError, Result},
+    files::FileServiceClient,
     models::*,
-    repositories::{LicenseRepository, QuotaRepository, BillingRepository, ComplianceRepository},
+    modules::ModuleServiceClient,
+    notifications::EmailClient,
+    pricing::{self, ExchangeRateClient},
+    promotions,
+    quota_cache::{CachedQuotaLimit, CounterUpdate, QuotaCache},
+    repositories::{LicenseRepository, QuotaRepository, BillingRepository, BillingDocumentRepository, ComplianceRepository, SeatRepository, DunningRepository, WebhookEventRepository, PromotionRepository, TaxRepository, PricingRepository},
+    tax::{self, TaxService, TaxableLine},
+    tenants::TenantServiceClient,
     workflows::*,
 };
 
+/// Upper bound on the tenants a single usage roll-up may cover
+const MAX_ROLLUP_TENANTS: usize = 500;
+
 #[derive(Clone)]
 pub struct LicenseService {
     license_repo: LicenseRepository,
     quota_repo: QuotaRepository,
     billing_repo: BillingRepository,
     compliance_repo: ComplianceRepository,
+    seat_repo: SeatRepository,
+    dunning_repo: DunningRepository,
+    webhook_repo: WebhookEventRepository,
+    document_repo: BillingDocumentRepository,
+    promotion_repo: PromotionRepository,
+    tax_repo: TaxRepository,
+    pricing_repo: PricingRepository,
     billing_service: BillingService,
+    tax_service: TaxService,
+    exchange_rate_client: ExchangeRateClient,
+    currency_config: CurrencyConfig,
+    grace_config: GraceConfig,
+    quota_cache: QuotaCache,
+    branding_client: BrandingClient,
+    file_client: FileServiceClient,
+    module_client: ModuleServiceClient,
     activities: LicenseActivities,
 }
 
@@ -27,14 +56,41 @@ impl LicenseService {
         quota_repo: QuotaRepository,
         billing_repo: BillingRepository,
         compliance_repo: ComplianceRepository,
+        seat_repo: SeatRepository,
+        dunning_repo: DunningRepository,
+        webhook_repo: WebhookEventRepository,
+        document_repo: BillingDocumentRepository,
+        promotion_repo: PromotionRepository,
+        tax_repo: TaxRepository,
+        pricing_repo: PricingRepository,
         billing_service: BillingService,
+        tax_service: TaxService,
+        exchange_rate_client: ExchangeRateClient,
+        tenant_client: TenantServiceClient,
+        email_client: EmailClient,
+        dunning_config: DunningConfig,
+        currency_config: CurrencyConfig,
+        grace_config: GraceConfig,
+        quota_cache: QuotaCache,
+        branding_client: BrandingClient,
+        file_client: FileServiceClient,
+        module_client: ModuleServiceClient,
     ) -> Self {
         let activities = LicenseActivities::new(
             license_repo.clone(),
             quota_repo.clone(),
             billing_repo.clone(),
             compliance_repo.clone(),
+            seat_repo.clone(),
+            dunning_repo.clone(),
+            webhook_repo.clone(),
+            promotion_repo.clone(),
+            pricing_repo.clone(),
             billing_service.clone(),
+            tenant_client,
+            email_client,
+            dunning_config,
+            grace_config.clone(),
         );
 
         Self {
@@ -42,7 +98,22 @@ impl LicenseService {
             quota_repo,
             billing_repo,
             compliance_repo,
+            seat_repo,
+            dunning_repo,
+            webhook_repo,
+            document_repo,
+            promotion_repo,
+            tax_repo,
+            pricing_repo,
             billing_service,
+            tax_service,
+            exchange_rate_client,
+            currency_config,
+            grace_config,
+            quota_cache,
+            branding_client,
+            file_client,
+            module_client,
             activities,
         }
     }
@@ -83,6 +154,96 @@ impl LicenseService {
         self.license_repo.get_expiring_licenses(days_ahead).await
     }
 
+    /// The tenant's plan features, quota limits and module licenses. Features
+    /// restricted by open dunning cases are left out, and a tenant without
+    /// an active license has none.
+    pub async fn get_entitlements(&self, tenant_id: Uuid) -> Result<Entitlements> {
+        let license = self.license_repo.get_latest_by_tenant_id(tenant_id).await?;
+        let active = license.as_ref().is_some_and(|license| license.is_active());
+
+        // A lapsed license leaves the tenant read-only until its grace period
+        // ends; tenants that never had a license are left to their features
+        let lapsed_since = license.as_ref().and_then(|license| license.lapsed_since());
+        let grace_ends_at = lapsed_since
+            .filter(|_| self.grace_config.enabled)
+            .map(|since| since + chrono::Duration::days(self.grace_config.period_days));
+        let access_mode = match (lapsed_since, grace_ends_at) {
+            (None, _) => AccessMode::Full,
+            (Some(_), Some(ends_at)) if ends_at > Utc::now() => AccessMode::ReadOnly,
+            (Some(_), _) => AccessMode::Suspended,
+        };
+        // Read-only tenants still see what their plan gave them
+        let entitled = active || access_mode == AccessMode::ReadOnly;
+
+        let mut restricted_features: Vec<String> = Vec::new();
+        for case in self.dunning_repo.list_for_tenant(tenant_id).await? {
+            if case.is_open() {
+                for feature in case.restricted_features {
+                    if !restricted_features.contains(&feature) {
+                        restricted_features.push(feature);
+                    }
+                }
+            }
+        }
+        restricted_features.sort();
+
+        let mut features = match license.as_ref().map(|license| &license.features) {
+            Some(_) if !entitled => Vec::new(),
+            // Features are a list of names, or a map of names to whether they are on
+            Some(serde_json::Value::Array(names)) => names.iter()
+                .filter_map(|name| name.as_str().map(str::to_string))
+                .collect(),
+            Some(serde_json::Value::Object(flags)) => flags.iter()
+                .filter(|(_, enabled)| enabled.as_bool().unwrap_or(false))
+                .map(|(name, _)| name.clone())
+                .collect(),
+            _ => Vec::new(),
+        };
+        features.retain(|feature| !restricted_features.contains(feature));
+        features.sort();
+        features.dedup();
+
+        let definitions = self.quota_repo.get_quota_definitions().await?;
+        let quotas = self.quota_repo.get_tenant_quotas(tenant_id).await?
+            .into_iter()
+            .filter_map(|quota| {
+                let definition = definitions.iter().find(|d| d.id == quota.quota_definition_id)?;
+                Some(QuotaEntitlement {
+                    quota_name: definition.name.clone(),
+                    quota_limit: quota.quota_limit,
+                    current_usage: quota.current_usage,
+                    remaining: quota.remaining(),
+                    unit: definition.unit.clone(),
+                })
+            })
+            .collect();
+
+        let modules = if entitled {
+            self.module_client.get_module_entitlements(tenant_id).await?
+        } else {
+            Vec::new()
+        };
+
+        Ok(Entitlements {
+            tenant_id,
+            subscription_tier: license.as_ref().map(|license| license.subscription_tier.clone()),
+            license_status: license.map(|license| license.status),
+            active,
+            features,
+            restricted_features,
+            quotas,
+            modules,
+            access_mode,
+            grace_ends_at,
+            generated_at: Utc::now(),
+        })
+    }
+
+    // Subscription change methods
+    pub async fn preview_subscription_change(&self, request: SubscriptionChangeRequest) -> Result<ProrationPreview> {
+        self.activities.preview_subscription_change(request).await
+    }
+
     // Quota management methods
     pub async fn check_quota(&self, tenant_id: Uuid, quota_name: &str, requested_amount: i64) -> Result<QuotaCheckResult> {
         let request = CheckQuotaRequest {
@@ -144,7 +305,507 @@ impl LicenseService {
     }
 
     pub async fn reset_quota(&self, tenant_id: Uuid, quota_name: &str) -> Result<()> {
-        self.quota_repo.reset_quota_usage(tenant_id, quota_name).await
+        self.quota_repo.reset_quota_usage(tenant_id, quota_name).await?;
+
+        if let Some(definition) = self.quota_repo.get_quota_definition_by_name(quota_name).await? {
+            if let Err(e) = self.quota_cache.invalidate(tenant_id, quota_name, definition.reset_period).await {
+                tracing::warn!("Failed to drop cached usage of {} for tenant {}: {}", quota_name, tenant_id, e);
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn get_quota_definitions(&self) -> Result<Vec<QuotaDefinition>> {
+        self.quota_repo.get_quota_definitions().await
+    }
+
+    pub async fn create_quota_definition(&self, request: CreateQuotaDefinitionRequest) -> Result<QuotaDefinition> {
+        if request.name.trim().is_empty() || request.unit.trim().is_empty() {
+            return Err(LicenseError::ValidationError("Quota name and unit are required".to_string()));
+        }
+        if !(1..=100).contains(&request.warning_threshold_percent.unwrap_or(80)) {
+            return Err(LicenseError::ValidationError("Warning threshold must be between 1 and 100 percent".to_string()));
+        }
+
+        self.quota_repo.create_quota_definition(request).await
+    }
+
+    /// Tenant limits already handed out keep their value; cached limits
+    /// pick up enforcement changes within `quotas.limit_cache_seconds`
+    pub async fn update_quota_definition(&self, name: &str, request: UpdateQuotaDefinitionRequest) -> Result<QuotaDefinition> {
+        if !(1..=100).contains(&request.warning_threshold_percent.unwrap_or(80)) {
+            return Err(LicenseError::ValidationError("Warning threshold must be between 1 and 100 percent".to_string()));
+        }
+
+        self.quota_repo.update_quota_definition(name, request).await?
+            .ok_or_else(|| LicenseError::QuotaNotFound { quota_name: name.to_string() })
+    }
+
+    /// Check and count usage in one step against the Redis counter of the
+    /// quota's current period. Falls back to the database when Redis is down.
+    pub async fn consume_quota(&self, request: ConsumeQuotaRequest) -> Result<QuotaCheckResult> {
+        if request.amount < 0 {
+            return Err(LicenseError::ValidationError("Amount must not be negative".to_string()));
+        }
+
+        match self.update_quota_counter(&request, request.amount).await {
+            Err(LicenseError::Redis(e)) => {
+                tracing::warn!("Quota cache unavailable, counting {} in the database: {}", request.quota_name, e);
+                self.consume_quota_in_database(request).await
+            }
+            result => result,
+        }
+    }
+
+    /// Give back usage of an allocation quota, e.g. after a file is deleted
+    pub async fn release_quota(&self, request: ConsumeQuotaRequest) -> Result<QuotaCheckResult> {
+        if request.amount < 0 {
+            return Err(LicenseError::ValidationError("Amount must not be negative".to_string()));
+        }
+
+        match self.update_quota_counter(&request, -request.amount).await {
+            Err(LicenseError::Redis(e)) => {
+                tracing::warn!("Quota cache unavailable, releasing {} in the database: {}", request.quota_name, e);
+                let quota = self.quota_repo.update_quota_usage(request.tenant_id, &request.quota_name, -request.amount).await?;
+                let definition = self.quota_repo.get_quota_definition_by_name(&request.quota_name).await?
+                    .ok_or_else(|| LicenseError::QuotaNotFound { quota_name: request.quota_name.clone() })?;
+
+                Ok(QuotaCheckResult {
+                    allowed: true,
+                    current_usage: quota.current_usage,
+                    quota_limit: quota.quota_limit,
+                    remaining: quota.remaining(),
+                    warning_threshold_reached: quota.is_warning_threshold_reached(definition.warning_threshold_percent),
+                    quota_name: request.quota_name,
+                })
+            }
+            result => result,
+        }
+    }
+
+    async fn update_quota_counter(&self, request: &ConsumeQuotaRequest, amount: i64) -> Result<QuotaCheckResult> {
+        let limit = self.cached_quota_limit(request.tenant_id, &request.quota_name).await?;
+        let now = Utc::now();
+        let hard_limit = if limit.enforce_hard_limit { limit.quota_limit } else { -1 };
+
+        let mut update = self.quota_cache
+            .update(request.tenant_id, &request.quota_name, limit.reset_period, now, amount, hard_limit, None)
+            .await?;
+
+        // Start the period's counter from what the database has recorded
+        if update == CounterUpdate::Unseeded {
+            if let Some((window_start, _)) = limit.reset_period.window(now) {
+                self.quota_repo.roll_quota_window(request.tenant_id, &request.quota_name, window_start).await?;
+            }
+            let recorded = self.quota_repo.get_tenant_quota(request.tenant_id, &request.quota_name).await?
+                .ok_or_else(|| LicenseError::QuotaNotFound { quota_name: request.quota_name.clone() })?;
+
+            update = self.quota_cache
+                .update(request.tenant_id, &request.quota_name, limit.reset_period, now, amount, hard_limit, Some(recorded.current_usage))
+                .await?;
+        }
+
+        let (current_usage, allowed) = match update {
+            CounterUpdate::Applied(usage) => (usage, true),
+            CounterUpdate::Refused(usage) => (usage, false),
+            CounterUpdate::Unseeded => {
+                return Err(LicenseError::Internal(format!("Counter of {} could not be seeded", request.quota_name)));
+            }
+        };
+
+        // The database follows the counter in the background so the
+        // caller only waits for Redis
+        if allowed && amount != 0 {
+            let quota_repo = self.quota_repo.clone();
+            let tenant_id = request.tenant_id;
+            let quota_name = request.quota_name.clone();
+            tokio::spawn(async move {
+                if let Err(e) = quota_repo.update_quota_usage(tenant_id, &quota_name, amount).await {
+                    tracing::error!("Failed to record usage of {} for tenant {}: {}", quota_name, tenant_id, e);
+                }
+            });
+        }
+
+        Ok(QuotaCheckResult {
+            allowed,
+            current_usage,
+            quota_limit: limit.quota_limit,
+            remaining: if limit.quota_limit < 0 { -1 } else { (limit.quota_limit - current_usage).max(0) },
+            warning_threshold_reached: limit.quota_limit > 0
+                && current_usage * 100 >= limit.quota_limit * limit.warning_threshold_percent as i64,
+            quota_name: request.quota_name.clone(),
+        })
+    }
+
+    async fn cached_quota_limit(&self, tenant_id: Uuid, quota_name: &str) -> Result<CachedQuotaLimit> {
+        if let Some(limit) = self.quota_cache.get_limit(tenant_id, quota_name).await? {
+            return Ok(limit);
+        }
+
+        let quota = self.quota_repo.get_tenant_quota(tenant_id, quota_name).await?
+            .ok_or_else(|| LicenseError::QuotaNotFound { quota_name: quota_name.to_string() })?;
+        let definition = self.quota_repo.get_quota_definition_by_name(quota_name).await?
+            .ok_or_else(|| LicenseError::QuotaNotFound { quota_name: quota_name.to_string() })?;
+
+        let limit = CachedQuotaLimit {
+            quota_limit: quota.quota_limit,
+            enforce_hard_limit: definition.enforce_hard_limit,
+            warning_threshold_percent: definition.warning_threshold_percent,
+            reset_period: definition.reset_period,
+        };
+        self.quota_cache.put_limit(tenant_id, quota_name, &limit).await?;
+        Ok(limit)
+    }
+
+    async fn consume_quota_in_database(&self, request: ConsumeQuotaRequest) -> Result<QuotaCheckResult> {
+        let definition = self.quota_repo.get_quota_definition_by_name(&request.quota_name).await?
+            .ok_or_else(|| LicenseError::QuotaNotFound { quota_name: request.quota_name.clone() })?;
+        if let Some((window_start, _)) = definition.reset_period.window(Utc::now()) {
+            self.quota_repo.roll_quota_window(request.tenant_id, &request.quota_name, window_start).await?;
+        }
+
+        let check = self.check_quota(request.tenant_id, &request.quota_name, request.amount).await?;
+        if !check.allowed {
+            return Ok(check);
+        }
+
+        let quota = self.quota_repo.update_quota_usage(request.tenant_id, &request.quota_name, request.amount).await?;
+        Ok(QuotaCheckResult {
+            allowed: true,
+            current_usage: quota.current_usage,
+            quota_limit: quota.quota_limit,
+            remaining: quota.remaining(),
+            warning_threshold_reached: quota.is_warning_threshold_reached(definition.warning_threshold_percent),
+            quota_name: request.quota_name,
+        })
+    }
+
+    // Seat management methods
+    pub async fn assign_seat(&self, request: AssignSeatRequest) -> Result<SeatAssignment> {
+        self.seat_repo.assign(request).await
+    }
+
+    pub async fn unassign_seat(&self, request: UnassignSeatRequest) -> Result<bool> {
+        self.seat_repo.unassign(request).await
+    }
+
+    pub async fn get_seat_summary(&self, tenant_id: Uuid) -> Result<SeatSummary> {
+        let quota = self.quota_repo.get_tenant_quota(tenant_id, SEAT_QUOTA).await?
+            .ok_or_else(|| LicenseError::QuotaNotFound { quota_name: SEAT_QUOTA.to_string() })?;
+        let assignments = self.seat_repo.list(tenant_id).await?;
+        let assigned_seats = assignments.len() as i64;
+
+        Ok(SeatSummary {
+            tenant_id,
+            seat_limit: quota.quota_limit,
+            assigned_seats,
+            available_seats: if quota.quota_limit < 0 {
+                -1 // Unlimited
+            } else {
+                (quota.quota_limit - assigned_seats).max(0)
+            },
+            assignments,
+        })
+    }
+
+    pub async fn get_seat_reconciliations(&self, tenant_id: Uuid, limit: i64) -> Result<Vec<SeatReconciliation>> {
+        self.seat_repo.get_reconciliations(tenant_id, limit).await
+    }
+
+    /// Reconcile seats in process, the way the worker's nightly run does
+    pub async fn reconcile_seats(&self, request: SeatReconciliationWorkflowRequest) -> Result<SeatReconciliationWorkflowResult> {
+        let tenant_ids = match request.tenant_ids {
+            Some(tenant_ids) => tenant_ids,
+            None => self.activities.list_seat_tenants().await?,
+        };
+
+        let mut result = SeatReconciliationWorkflowResult::default();
+        for tenant_id in tenant_ids {
+            let reconcile_request = ReconcileTenantSeatsRequest {
+                tenant_id,
+                adjust_billing: request.adjust_billing,
+            };
+
+            match self.activities.reconcile_tenant_seats(reconcile_request).await {
+                Ok(reconciliation) => {
+                    result.tenants_checked += 1;
+                    if reconciliation.drifted() {
+                        result.tenants_drifted += 1;
+                    }
+                    if reconciliation.usage_corrected {
+                        result.usage_corrections += 1;
+                    }
+                    if reconciliation.billing_corrected {
+                        result.billing_corrections += 1;
+                    }
+                    if reconciliation.error.is_some() {
+                        result.failed_tenants.push(tenant_id);
+                    }
+                }
+                Err(e) => {
+                    tracing::warn!("Seat reconciliation failed for tenant {}: {:?}", tenant_id, e);
+                    result.failed_tenants.push(tenant_id);
+                }
+            }
+        }
+
+        Ok(result)
+    }
+
+    // Dunning methods
+    pub async fn list_dunning_cases(&self, status: Option<DunningStatus>, limit: i64, offset: i64) -> Result<Vec<DunningCase>> {
+        self.dunning_repo.list(status, limit, offset).await
+    }
+
+    pub async fn get_dunning_case(&self, case_id: Uuid) -> Result<Option<DunningCase>> {
+        self.dunning_repo.get(case_id).await
+    }
+
+    pub async fn get_tenant_dunning_cases(&self, tenant_id: Uuid) -> Result<Vec<DunningCase>> {
+        self.dunning_repo.list_for_tenant(tenant_id).await
+    }
+
+    /// Hold a case's retries and escalation, e.g. while the tenant sorts out
+    /// its payment method with support
+    pub async fn pause_dunning_case(&self, case_id: Uuid, request: PauseDunningRequest) -> Result<DunningCase> {
+        let case = self.dunning_repo.pause(case_id, request.paused_by).await?;
+        tracing::info!("Paused dunning case {}", case.id);
+        Ok(case)
+    }
+
+    pub async fn resume_dunning_case(&self, case_id: Uuid) -> Result<DunningCase> {
+        let case = self.dunning_repo.resume(case_id).await?;
+        tracing::info!("Resumed dunning case {}", case.id);
+        Ok(case)
+    }
+
+    /// Waive the invoice: restrictions are lifted and a suspended tenant
+    /// reactivated without payment
+    pub async fn forgive_dunning_case(&self, case_id: Uuid, request: ForgiveDunningRequest) -> Result<DunningCase> {
+        let case = self.dunning_repo.get(case_id).await?
+            .ok_or_else(|| LicenseError::DunningCaseNotFound(case_id.to_string()))?;
+        if !case.is_open() && case.status != DunningStatus::Suspended {
+            return Err(LicenseError::ValidationError(format!(
+                "Dunning case {} is already {:?}", case_id, case.status
+            )));
+        }
+
+        self.activities.resolve_dunning_case(ResolveDunningCaseRequest {
+            case_id,
+            status: DunningStatus::Forgiven,
+            resolved_by: request.forgiven_by,
+            notes: request.notes,
+        }).await
+    }
+
+    /// Run a case's due retry in process, the way the worker does
+    pub async fn process_dunning_case(&self, case_id: Uuid) -> Result<DunningWorkflowResult> {
+        let case = self.activities.get_dunning_case(DunningCaseRequest { case_id }).await?;
+        if case.status != DunningStatus::Active {
+            return Ok(case.into());
+        }
+
+        let charge = self.activities.retry_dunning_charge(DunningCaseRequest { case_id }).await?;
+        let case = if charge.paid {
+            self.activities.resolve_dunning_case(ResolveDunningCaseRequest {
+                case_id,
+                status: DunningStatus::Recovered,
+                resolved_by: None,
+                notes: Some("Paid on retry".to_string()),
+            }).await?
+        } else if charge.case.next_attempt_at.is_some() {
+            self.activities.restrict_dunning_features(DunningCaseRequest { case_id }).await?
+        } else {
+            self.activities.suspend_dunning_tenant(DunningCaseRequest { case_id }).await?
+        };
+
+        Ok(case.into())
+    }
+
+    /// Run every case whose retry is due; returns how many were processed
+    pub async fn process_due_dunning_cases(&self) -> Result<usize> {
+        let cases = self.activities.list_due_dunning_cases().await?;
+        let mut processed = 0;
+        for case in cases {
+            match self.process_dunning_case(case.id).await {
+                Ok(result) => {
+                    processed += 1;
+                    tracing::info!(
+                        "Dunning case {} is {:?} after {} retries",
+                        result.case_id,
+                        result.status,
+                        result.attempt_count
+                    );
+                }
+                Err(e) => tracing::warn!("Dunning retry failed for case {}: {}", case.id, e),
+            }
+        }
+        Ok(processed)
+    }
+
+    // Billing webhook methods
+    pub async fn handle_stripe_webhook(&self, payload: &str, signature: &str) -> Result<Option<BillingWebhookWorkflowResult>> {
+        let event = self.billing_service.verify_webhook(payload, signature)?;
+        self.ingest_webhook_event("stripe", &event.id, &event.event_type, serde_json::from_str(payload)?).await
+    }
+
+    pub async fn handle_paypal_webhook(&self, headers: &PayPalWebhookHeaders, payload: &str) -> Result<Option<BillingWebhookWorkflowResult>> {
+        let event = self.billing_service.verify_paypal_webhook(headers, payload).await?;
+        self.ingest_webhook_event("paypal", &event.id, &event.event_type, serde_json::from_str(payload)?).await
+    }
+
+    /// Record a verified event and apply it. Returns `None` for a
+    /// redelivery of an event that was already applied.
+    async fn ingest_webhook_event(
+        &self,
+        provider: &str,
+        event_id: &str,
+        event_type: &str,
+        payload: serde_json::Value,
+    ) -> Result<Option<BillingWebhookWorkflowResult>> {
+        let Some(event) = self.webhook_repo.record(provider, event_id, event_type, &payload).await? else {
+            tracing::debug!("Skipping duplicate {} event {}", provider, event_id);
+            return Ok(None);
+        };
+
+        self.process_webhook_event(event.id).await.map(Some)
+    }
+
+    /// Apply a recorded event in process, the way the billing webhook
+    /// workflow does
+    pub async fn process_webhook_event(&self, webhook_event_id: Uuid) -> Result<BillingWebhookWorkflowResult> {
+        let (status, error) = match self.activities.apply_billing_event(WebhookEventRequest { webhook_event_id }).await {
+            Ok(status) => (status, None),
+            Err(e) => {
+                tracing::warn!("Failed to apply webhook event {}: {}", webhook_event_id, e);
+                (WebhookEventStatus::Failed, Some(e.to_string()))
+            }
+        };
+
+        self.activities.mark_webhook_event(MarkWebhookEventRequest {
+            webhook_event_id,
+            status: status.clone(),
+            error: error.clone(),
+        }).await?;
+
+        Ok(BillingWebhookWorkflowResult {
+            webhook_event_id,
+            status,
+            error,
+        })
+    }
+
+    pub async fn list_webhook_events(&self, provider: Option<&str>, status: Option<WebhookEventStatus>, limit: i64, offset: i64) -> Result<Vec<WebhookEvent>> {
+        self.webhook_repo.list(provider, status, limit, offset).await
+    }
+
+    pub async fn get_webhook_event(&self, id: Uuid) -> Result<Option<WebhookEvent>> {
+        self.webhook_repo.get(id).await
+    }
+
+    /// Apply an event again, e.g. after fixing what made it fail
+    pub async fn replay_webhook_event(&self, id: Uuid) -> Result<BillingWebhookWorkflowResult> {
+        let event = self.webhook_repo.reopen(id).await?;
+        tracing::info!("Replaying {} event {} ({})", event.provider, event.event_id, event.event_type);
+        self.process_webhook_event(event.id).await
+    }
+
+    // Promotion methods
+    pub async fn create_coupon(&self, request: CreateCouponRequest) -> Result<Coupon> {
+        promotions::validate_new_coupon(&request)?;
+
+        let code = promotions::normalize_code(&request.code);
+        let eligible_tiers: Vec<String> = request.eligible_tiers.iter()
+            .flatten()
+            .map(|tier| tier.as_str().to_string())
+            .collect();
+        let request = CreateCouponRequest {
+            currency: request.currency.as_deref().map(str::to_uppercase),
+            ..request
+        };
+
+        self.promotion_repo.create_coupon(&code, &request, &eligible_tiers).await
+    }
+
+    pub async fn list_coupons(&self, active_only: bool, limit: i64, offset: i64) -> Result<Vec<Coupon>> {
+        self.promotion_repo.list_coupons(active_only, limit, offset).await
+    }
+
+    pub async fn update_coupon(&self, code: &str, request: UpdateCouponRequest) -> Result<Coupon> {
+        if request.max_redemptions.is_some_and(|max| max < 1) {
+            return Err(LicenseError::ValidationError("Redemption limits must be at least 1".to_string()));
+        }
+
+        let code = promotions::normalize_code(code);
+        self.promotion_repo.update_coupon(&code, &request).await?
+            .ok_or(LicenseError::CouponNotFound(code))
+    }
+
+    pub async fn get_coupon_redemptions(&self, code: &str, limit: i64, offset: i64) -> Result<Vec<CouponRedemption>> {
+        let code = promotions::normalize_code(code);
+        let coupon = self.promotion_repo.get_coupon_by_code(&code).await?
+            .ok_or(LicenseError::CouponNotFound(code))?;
+
+        self.promotion_repo.list_redemptions(coupon.id, limit, offset).await
+    }
+
+    pub async fn validate_promotion(&self, request: ValidatePromotionRequest) -> Result<PromotionQuote> {
+        if request.amount < Decimal::ZERO {
+            return Err(LicenseError::ValidationError("Amount can't be negative".to_string()));
+        }
+        self.activities.validate_promotion(ValidatePromotionRequest {
+            currency: request.currency.to_uppercase(),
+            ..request
+        }).await
+    }
+
+    pub async fn grant_credit(&self, request: GrantCreditRequest) -> Result<CreditEntry> {
+        if request.amount <= Decimal::ZERO {
+            return Err(LicenseError::ValidationError("Credit grants must be positive".to_string()));
+        }
+        if request.reason.trim().is_empty() {
+            return Err(LicenseError::ValidationError("Credit grants need a reason".to_string()));
+        }
+
+        let request = GrantCreditRequest {
+            currency: request.currency.to_uppercase(),
+            ..request
+        };
+        let entry = self.promotion_repo.grant_credit(&request).await?;
+
+        let compliance_log = ComplianceLog {
+            id: Uuid::new_v4(),
+            tenant_id: entry.tenant_id,
+            event_type: "credit_granted".to_string(),
+            event_category: "billing".to_string(),
+            severity: "info".to_string(),
+            description: format!("Credit granted: {} {}", entry.amount, entry.currency),
+            details: Some(serde_json::json!({
+                "credit_entry_id": entry.id,
+                "amount": entry.amount,
+                "currency": entry.currency,
+                "reason": entry.reason
+            })),
+            user_id: request.granted_by,
+            resource_id: Some(entry.id),
+            ip_address: None,
+            resolved: true,
+            resolved_at: Some(Utc::now()),
+            resolved_by: request.granted_by,
+            resolution_notes: None,
+            created_at: Utc::now(),
+        };
+        self.compliance_repo.log_compliance_event(compliance_log).await?;
+
+        Ok(entry)
+    }
+
+    pub async fn get_tenant_credits(&self, tenant_id: Uuid, limit: i64, offset: i64) -> Result<TenantCredits> {
+        Ok(TenantCredits {
+            tenant_id,
+            balances: self.promotion_repo.credit_balances(tenant_id).await?,
+            entries: self.promotion_repo.list_credit_entries(tenant_id, limit, offset).await?,
+        })
     }
 
     // Billing methods
@@ -157,7 +818,108 @@ impl LicenseService {
     }
 
     pub async fn update_payment_status(&self, billing_id: Uuid, status: PaymentStatus, payment_reference: Option<String>) -> Result<()> {
-        self.billing_repo.update_payment_status(billing_id, status, payment_reference).await
+        let paid = matches!(status, PaymentStatus::Completed);
+        self.billing_repo.update_payment_status(billing_id, status, payment_reference).await?;
+
+        // The worker picks up receipts that fail here
+        if paid {
+            if let Err(e) = self.generate_billing_documents(billing_id).await {
+                tracing::warn!("Failed to generate receipt for billing record {}: {}", billing_id, e);
+            }
+        }
+        Ok(())
+    }
+
+    /// Generate the record's invoice, and its receipt once paid. Documents
+    /// already generated are kept, so this can be repeated.
+    pub async fn generate_billing_documents(&self, billing_id: Uuid) -> Result<Vec<BillingDocument>> {
+        let record = self.billing_repo.get_by_id(billing_id).await?
+            .ok_or_else(|| LicenseError::BillingRecordNotFound(billing_id.to_string()))?;
+        self.generate_documents_for_record(&record).await
+    }
+
+    /// Generate documents missing from up to `limit` billing records,
+    /// returning how many records were completed
+    pub async fn generate_missing_billing_documents(&self, limit: i64) -> Result<usize> {
+        let records = self.document_repo.get_records_missing_documents(limit).await?;
+        let mut completed = 0;
+
+        for record in &records {
+            match self.generate_documents_for_record(record).await {
+                Ok(_) => completed += 1,
+                Err(e) => tracing::warn!("Failed to generate documents for billing record {}: {}", record.id, e),
+            }
+        }
+
+        Ok(completed)
+    }
+
+    async fn generate_documents_for_record(&self, record: &BillingHistory) -> Result<Vec<BillingDocument>> {
+        let mut documents = self.document_repo.list_for_record(record.id).await?;
+
+        let mut wanted = vec![BillingDocumentType::Invoice];
+        if matches!(record.payment_status, PaymentStatus::Completed) {
+            wanted.push(BillingDocumentType::Receipt);
+        }
+        wanted.retain(|document_type| !documents.iter().any(|document| document.document_type == *document_type));
+        if wanted.is_empty() {
+            return Ok(documents);
+        }
+
+        let branding = self.branding_client.get(record.tenant_id).await;
+        for document_type in wanted {
+            let issued_at = Utc::now();
+            let document_number = documents::document_number(document_type, record);
+            let filename = documents::document_filename(document_type, &document_number);
+            let content = documents::render_billing_document(document_type, record, &document_number, issued_at, &branding);
+            let file_size = content.len() as i64;
+
+            let metadata = serde_json::json!({
+                "source": "license-service",
+                "billing_history_id": record.id,
+                "document_type": document_type.as_str(),
+                "document_number": document_number
+            });
+            let file_id = self.file_client
+                .upload(record.tenant_id, &filename, "application/pdf", content, &metadata)
+                .await?;
+
+            let document = self.document_repo.create(BillingDocument {
+                id: Uuid::new_v4(),
+                tenant_id: record.tenant_id,
+                billing_history_id: record.id,
+                document_type,
+                document_number,
+                file_id,
+                filename,
+                file_size,
+                amount: record.amount,
+                currency: record.currency.clone(),
+                issued_at,
+            }).await?;
+            documents.push(document);
+        }
+
+        Ok(documents)
+    }
+
+    pub async fn list_billing_documents(&self, tenant_id: Uuid, limit: i64, offset: i64) -> Result<Vec<BillingDocument>> {
+        self.document_repo.list_for_tenant(tenant_id, limit, offset).await
+    }
+
+    /// A short-lived download link for a document of the tenant's
+    pub async fn get_billing_document_download(&self, tenant_id: Uuid, document_id: Uuid) -> Result<BillingDocumentDownload> {
+        let document = self.document_repo.get(document_id).await?
+            .filter(|document| document.tenant_id == tenant_id)
+            .ok_or_else(|| LicenseError::BillingDocumentNotFound(document_id.to_string()))?;
+
+        let (download_url, expires_at) = self.file_client.download_url(document.tenant_id, document.file_id).await?;
+        Ok(BillingDocumentDownload {
+            document_id: document.id,
+            filename: document.filename,
+            download_url,
+            expires_at,
+        })
     }
 
     pub async fn generate_invoice(&self, tenant_id: Uuid, license_id: Uuid) -> Result<BillingInvoice> {
@@ -169,28 +931,479 @@ impl LicenseService {
         let invoice_number = self.billing_service.generate_invoice_number().await;
 
         // Create basic subscription invoice
-        let line_items = vec![
+        let mut line_items = vec![
             BillingLineItem {
                 description: format!("Subscription - {:?}", license.subscription_tier),
                 quantity: 1,
                 unit_price: license.base_price,
                 total_price: license.base_price,
                 item_type: "subscription".to_string(),
+                tax_amount: Decimal::ZERO,
+                tax_breakdown: Vec::new(),
             }
         ];
 
-        let tax_amount = license.base_price * Decimal::from_str("0.08").unwrap_or_default(); // 8% tax
+        // Add marketplace module usage not invoiced yet, one line per module
+        let module_usage: Vec<ModuleUsageCharge> = self.billing_repo.get_uninvoiced_module_usage(tenant_id).await?
+            .into_iter()
+            .filter(|charge| charge.currency == license.currency)
+            .collect();
+        let mut usage_by_module: std::collections::BTreeMap<&str, Decimal> = std::collections::BTreeMap::new();
+        for charge in &module_usage {
+            *usage_by_module.entry(charge.module_id.as_str()).or_insert(Decimal::ZERO) += charge.amount;
+        }
+        let mut usage_total = Decimal::ZERO;
+        for (module_id, amount) in &usage_by_module {
+            let amount = amount.round_dp(2);
+            line_items.push(BillingLineItem {
+                description: format!("Module usage - {}", module_id),
+                quantity: 1,
+                unit_price: amount,
+                total_price: amount,
+                item_type: "module_usage".to_string(),
+                tax_amount: Decimal::ZERO,
+                tax_breakdown: Vec::new(),
+            });
+            usage_total += amount;
+        }
+
+        let subtotal = license.base_price + usage_total;
+
+        // Tax each line with the configured provider
+        let profile = self.tax_repo.get_profile(tenant_id).await?;
+        let taxable_lines = line_items.iter()
+            .enumerate()
+            .map(|(i, item)| TaxableLine {
+                reference: format!("line_{}", i),
+                description: item.description.clone(),
+                amount: item.total_price,
+            })
+            .collect();
+        let invoice_tax = self.tax_service
+            .calculate_invoice_tax(profile.as_ref(), &license.currency, taxable_lines)
+            .await?;
+        for (item, line_tax) in line_items.iter_mut().zip(&invoice_tax.lines) {
+            item.tax_amount = line_tax.tax_amount;
+            item.tax_breakdown = line_tax.breakdown.clone();
+        }
+        let tax_amount = invoice_tax.total();
+
+        let customer_country = profile.as_ref().map(|profile| profile.country.clone()).unwrap_or_default();
+        let tax_lines: Vec<InvoiceTaxLine> = line_items.iter()
+            .enumerate()
+            .flat_map(|(i, item)| {
+                let breakdown = if invoice_tax.reverse_charge {
+                    vec![TaxBreakdown {
+                        tax_type: "vat".to_string(),
+                        jurisdiction: customer_country.clone(),
+                        rate: Decimal::ZERO,
+                        amount: Decimal::ZERO,
+                    }]
+                } else {
+                    item.tax_breakdown.clone()
+                };
+                breakdown.into_iter().map(move |tax| (i, item, tax))
+            })
+            .map(|(i, item, tax)| InvoiceTaxLine {
+                id: Uuid::new_v4(),
+                invoice_number: invoice_number.clone(),
+                tenant_id,
+                line_index: i as i32,
+                description: item.description.clone(),
+                taxable_amount: item.total_price,
+                tax_type: tax.tax_type,
+                jurisdiction: tax.jurisdiction,
+                rate: tax.rate,
+                tax_amount: tax.amount,
+                currency: license.currency.clone(),
+                provider: invoice_tax.provider.to_string(),
+                reverse_charge: invoice_tax.reverse_charge,
+                created_at: Utc::now(),
+            })
+            .collect();
+        self.tax_repo.record_invoice_tax_lines(&tax_lines).await?;
+
+        if !module_usage.is_empty() {
+            let charge_ids: Vec<Uuid> = module_usage.iter().map(|charge| charge.id).collect();
+            self.billing_repo.mark_module_usage_invoiced(&charge_ids, &invoice_number).await?;
+        }
+
+        // Keep the rate to the default currency the invoice was generated at,
+        // so it reports the same in the books however rates move afterwards
+        let amount = subtotal + tax_amount;
+        let default_currency = self.billing_service.config().default_currency.to_uppercase();
+        let exchange_rate = if license.currency.eq_ignore_ascii_case(&default_currency) {
+            None
+        } else {
+            match self.pricing_repo.latest_rate(&default_currency, &license.currency.to_uppercase()).await? {
+                Some(rate) => {
+                    let snapshot = InvoiceExchangeRate {
+                        invoice_number: invoice_number.clone(),
+                        tenant_id,
+                        base_currency: rate.base_currency.clone(),
+                        invoice_currency: rate.quote_currency.clone(),
+                        rate: rate.rate,
+                        source: rate.source.clone(),
+                        rate_effective_at: rate.effective_at,
+                        base_amount: pricing::convert_to_base(amount, &rate),
+                        created_at: Utc::now(),
+                    };
+                    self.pricing_repo.record_invoice_exchange_rate(&snapshot).await?;
+                    Some(snapshot)
+                }
+                None => {
+                    tracing::warn!(
+                        "No {}/{} exchange rate, invoice {} has no rate snapshot",
+                        default_currency, license.currency, invoice_number
+                    );
+                    None
+                }
+            }
+        };
 
         Ok(BillingInvoice {
             invoice_number,
             tenant_id,
-            amount: license.base_price + tax_amount,
+            amount,
             currency: license.currency,
             tax_amount,
             billing_period_start: Utc::now(),
             billing_period_end: Utc::now() + chrono::Duration::days(30),
             line_items,
             usage_summary: None,
+            reverse_charge: invoice_tax.reverse_charge,
+            tax_note: invoice_tax.note,
+            exchange_rate,
+        })
+    }
+
+    pub async fn get_invoice_tax_lines(&self, tenant_id: Uuid, invoice_number: &str) -> Result<Vec<InvoiceTaxLine>> {
+        self.tax_repo.get_invoice_tax_lines(tenant_id, invoice_number).await
+    }
+
+    pub async fn get_invoice_exchange_rate(&self, tenant_id: Uuid, invoice_number: &str) -> Result<InvoiceExchangeRate> {
+        self.pricing_repo.get_invoice_exchange_rate(tenant_id, invoice_number).await?
+            .ok_or_else(|| LicenseError::ExchangeRateNotFound(format!("invoice {}", invoice_number)))
+    }
+
+    // Tax methods
+    pub async fn get_tax_profile(&self, tenant_id: Uuid) -> Result<TenantTaxProfile> {
+        self.tax_repo.get_profile(tenant_id).await?
+            .ok_or_else(|| LicenseError::TaxProfileNotFound(tenant_id.to_string()))
+    }
+
+    /// Save the tenant's tax profile. A new tax ID is checked straight away;
+    /// when VIES can't be reached it stays unverified until checked again.
+    pub async fn upsert_tax_profile(&self, tenant_id: Uuid, request: UpsertTaxProfileRequest) -> Result<TenantTaxProfile> {
+        let country = request.country.trim().to_uppercase();
+        if country.len() != 2 || !country.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(LicenseError::ValidationError("Country must be an ISO 3166-1 alpha-2 code".to_string()));
+        }
+        let tax_id = request.tax_id.as_deref()
+            .map(tax::normalize_tax_id)
+            .filter(|tax_id| !tax_id.is_empty());
+        if let Some((prefix, _)) = tax_id.as_deref().and_then(tax::split_vat_number) {
+            let prefix_country = if prefix == "EL" { "GR" } else { prefix.as_str() };
+            if prefix_country != country {
+                return Err(LicenseError::ValidationError(format!(
+                    "VAT number {} is not a {} VAT number",
+                    tax_id.as_deref().unwrap_or_default(),
+                    country
+                )));
+            }
+        }
+
+        let request = UpsertTaxProfileRequest {
+            country,
+            region: request.region.map(|region| region.trim().to_uppercase()).filter(|region| !region.is_empty()),
+            ..request
+        };
+        let profile = self.tax_repo.upsert_profile(tenant_id, &request, tax_id.as_deref()).await?;
+
+        if profile.tax_id.is_some() && profile.tax_id_status == TaxIdStatus::Unverified {
+            match self.validate_tax_id(tenant_id).await {
+                Ok(checked) => return Ok(checked),
+                Err(e) => tracing::warn!("Tax ID of tenant {} left unverified: {}", tenant_id, e),
+            }
+        }
+        Ok(profile)
+    }
+
+    /// Check the tenant's tax ID again, e.g. before invoicing with reverse charge
+    pub async fn validate_tax_id(&self, tenant_id: Uuid) -> Result<TenantTaxProfile> {
+        let profile = self.get_tax_profile(tenant_id).await?;
+        let tax_id = profile.tax_id.clone()
+            .ok_or_else(|| LicenseError::ValidationError("The tenant has no tax ID".to_string()))?;
+
+        let status = self.tax_service.check_tax_id(&tax_id).await?;
+        let checked = self.tax_repo.set_tax_id_status(tenant_id, &tax_id, status).await?
+            .unwrap_or(profile);
+
+        let compliance_log = ComplianceLog {
+            id: Uuid::new_v4(),
+            tenant_id,
+            event_type: "tax_id_checked".to_string(),
+            event_category: "billing".to_string(),
+            severity: if status == TaxIdStatus::Invalid { "warning" } else { "info" }.to_string(),
+            description: format!("Tax ID {} checked: {:?}", tax_id, status),
+            details: Some(serde_json::json!({
+                "tax_id": tax_id,
+                "status": status,
+                "country": checked.country
+            })),
+            user_id: None,
+            resource_id: Some(tenant_id),
+            ip_address: None,
+            resolved: true,
+            resolved_at: Some(Utc::now()),
+            resolved_by: None,
+            resolution_notes: None,
+            created_at: Utc::now(),
+        };
+        self.compliance_repo.log_compliance_event(compliance_log).await?;
+
+        Ok(checked)
+    }
+
+    pub async fn list_tax_rates(&self, country: Option<&str>) -> Result<Vec<TaxRate>> {
+        let country = country.map(str::to_uppercase);
+        self.tax_repo.list_rates(country.as_deref()).await
+    }
+
+    pub async fn create_tax_rate(&self, request: CreateTaxRateRequest) -> Result<TaxRate> {
+        let country = request.country.trim().to_uppercase();
+        if country.len() != 2 || !country.chars().all(|c| c.is_ascii_alphabetic()) {
+            return Err(LicenseError::ValidationError("Country must be an ISO 3166-1 alpha-2 code".to_string()));
+        }
+        if !matches!(request.tax_type.as_str(), "vat" | "gst" | "sales_tax") {
+            return Err(LicenseError::ValidationError("Tax type must be vat, gst or sales_tax".to_string()));
+        }
+        if request.rate < Decimal::ZERO || request.rate > Decimal::ONE_HUNDRED {
+            return Err(LicenseError::ValidationError("Tax rates are percentages from 0 to 100".to_string()));
+        }
+
+        self.tax_repo.replace_rate(&CreateTaxRateRequest {
+            country,
+            region: request.region.map(|region| region.trim().to_uppercase()).filter(|region| !region.is_empty()),
+            ..request
+        }).await
+    }
+
+    // Pricing methods
+    /// Plan prices in `currency`, or else in the tenant's currency
+    pub async fn list_plan_prices(&self, tenant_id: Option<Uuid>, currency: Option<&str>) -> Result<Vec<PlanPrice>> {
+        let currency = match (currency, tenant_id) {
+            (Some(currency), _) => self.supported_currency(currency)?,
+            (None, Some(tenant_id)) => self.activities.tenant_currency(tenant_id).await?,
+            (None, None) => self.billing_service.config().default_currency.to_uppercase(),
+        };
+
+        let mut plans: Vec<(SubscriptionTier, BillingCycle)> = Vec::new();
+        for entry in self.pricing_repo.list_entries(None).await? {
+            let plan = (entry.subscription_tier, entry.billing_cycle);
+            if !plans.contains(&plan) {
+                plans.push(plan);
+            }
+        }
+
+        let mut prices = Vec::with_capacity(plans.len());
+        for (tier, cycle) in &plans {
+            prices.push(self.activities.plan_price(tier, cycle, &currency).await?);
+        }
+        Ok(prices)
+    }
+
+    pub async fn get_tenant_currency(&self, tenant_id: Uuid) -> Result<String> {
+        self.activities.tenant_currency(tenant_id).await
+    }
+
+    /// Price and bill the tenant in another currency. Licenses already
+    /// provisioned keep their currency until their plan changes.
+    pub async fn set_tenant_currency(&self, tenant_id: Uuid, request: SetTenantCurrencyRequest) -> Result<TenantBillingCurrency> {
+        let currency = self.supported_currency(&request.currency)?;
+        let default_currency = self.billing_service.config().default_currency.to_uppercase();
+        if currency != default_currency
+            && self.pricing_repo.list_entries(Some(&currency)).await?.is_empty()
+            && self.pricing_repo.latest_rate(&default_currency, &currency).await?.is_none()
+        {
+            return Err(LicenseError::ValidationError(format!(
+                "{} has no price book and no exchange rate from {}",
+                currency, default_currency
+            )));
+        }
+
+        let previous = self.activities.tenant_currency(tenant_id).await?;
+        let tenant_currency = self.pricing_repo.set_tenant_currency(tenant_id, &currency).await?;
+
+        let compliance_log = ComplianceLog {
+            id: Uuid::new_v4(),
+            tenant_id,
+            event_type: "billing_currency_changed".to_string(),
+            event_category: "billing".to_string(),
+            severity: "info".to_string(),
+            description: format!("Billing currency changed from {} to {}", previous, currency),
+            details: Some(serde_json::json!({
+                "previous_currency": previous,
+                "currency": currency
+            })),
+            user_id: None,
+            resource_id: Some(tenant_id),
+            ip_address: None,
+            resolved: true,
+            resolved_at: Some(Utc::now()),
+            resolved_by: None,
+            resolution_notes: None,
+            created_at: Utc::now(),
+        };
+        self.compliance_repo.log_compliance_event(compliance_log).await?;
+
+        Ok(tenant_currency)
+    }
+
+    pub async fn list_price_book(&self, currency: Option<&str>) -> Result<Vec<PriceBookEntry>> {
+        let currency = currency.map(pricing::normalize_currency).transpose()?;
+        self.pricing_repo.list_entries(currency.as_deref()).await
+    }
+
+    pub async fn upsert_price_book_entry(&self, request: UpsertPriceBookEntryRequest) -> Result<PriceBookEntry> {
+        let currency = self.supported_currency(&request.currency)?;
+        if request.price < Decimal::ZERO || request.setup_fee.is_some_and(|fee| fee < Decimal::ZERO) {
+            return Err(LicenseError::ValidationError("Prices and setup fees can't be negative".to_string()));
+        }
+
+        self.pricing_repo.upsert_entry(&UpsertPriceBookEntryRequest {
+            currency,
+            price: request.price.round_dp(2),
+            setup_fee: request.setup_fee.map(|fee| fee.round_dp(2)),
+            ..request
+        }).await
+    }
+
+    /// Latest rates from the default currency
+    pub async fn list_exchange_rates(&self) -> Result<Vec<ExchangeRate>> {
+        let default_currency = self.billing_service.config().default_currency.to_uppercase();
+        self.pricing_repo.latest_rates(&default_currency).await
+    }
+
+    /// Set a rate by hand, e.g. a contractual rate; it applies until the
+    /// next refresh records a newer one
+    pub async fn record_exchange_rate(&self, request: RecordExchangeRateRequest) -> Result<ExchangeRate> {
+        let base_currency = pricing::normalize_currency(&request.base_currency)?;
+        let quote_currency = pricing::normalize_currency(&request.quote_currency)?;
+        if base_currency == quote_currency {
+            return Err(LicenseError::ValidationError("An exchange rate needs two different currencies".to_string()));
+        }
+        if request.rate <= Decimal::ZERO {
+            return Err(LicenseError::ValidationError("Exchange rates must be positive".to_string()));
+        }
+
+        self.pricing_repo.record_rate(&base_currency, &quote_currency, request.rate, "manual", Utc::now()).await
+    }
+
+    /// Record the latest rates from the default currency to the other
+    /// supported currencies
+    pub async fn refresh_exchange_rates(&self) -> Result<usize> {
+        let default_currency = self.billing_service.config().default_currency.to_uppercase();
+        let currencies: Vec<String> = self.currency_config.supported.iter()
+            .map(|currency| currency.to_uppercase())
+            .filter(|currency| *currency != default_currency)
+            .collect();
+        if currencies.is_empty() {
+            return Ok(0);
+        }
+
+        let (effective_at, rates) = self.exchange_rate_client.latest(&default_currency, &currencies).await?;
+        for (quote_currency, rate) in &rates {
+            self.pricing_repo.record_rate(
+                &default_currency,
+                quote_currency,
+                *rate,
+                self.exchange_rate_client.source(),
+                effective_at,
+            ).await?;
+        }
+        Ok(rates.len())
+    }
+
+    fn supported_currency(&self, currency: &str) -> Result<String> {
+        let currency = pricing::normalize_currency(currency)?;
+        if !self.currency_config.supported.iter().any(|supported| supported.eq_ignore_ascii_case(&currency)) {
+            return Err(LicenseError::ValidationError(format!("{} is not a supported currency", currency)));
+        }
+        pricing::check_billable_currency(&currency)?;
+        Ok(currency)
+    }
+
+    pub async fn record_module_usage(&self, request: RecordModuleUsageRequest) -> Result<u64> {
+        let charges = self.billing_service.module_usage_charges(&request)?;
+        self.billing_repo.record_module_usage_charges(&charges).await
+    }
+
+    pub async fn get_developer_revenue(&self, developer_id: &str, start_date: DateTime<Utc>, end_date: DateTime<Utc>) -> Result<DeveloperRevenueReport> {
+        let modules = self.billing_repo.get_developer_revenue(developer_id, start_date, end_date).await?;
+
+        Ok(DeveloperRevenueReport {
+            developer_id: developer_id.to_string(),
+            period_start: start_date,
+            period_end: end_date,
+            modules,
+        })
+    }
+
+    /// Licenses, billing and quota usage of a set of tenants, with billing
+    /// totals per currency across all of them
+    pub async fn get_usage_rollup(&self, tenant_ids: &[Uuid], start_date: DateTime<Utc>, end_date: DateTime<Utc>) -> Result<UsageRollupReport> {
+        if tenant_ids.len() > MAX_ROLLUP_TENANTS {
+            return Err(LicenseError::ValidationError(format!(
+                "A roll-up covers at most {} tenants",
+                MAX_ROLLUP_TENANTS
+            )));
+        }
+
+        let billing_totals = self.billing_repo.get_billing_totals(tenant_ids, start_date, end_date).await?;
+
+        let mut tenants = Vec::with_capacity(tenant_ids.len());
+        let mut totals: Vec<CurrencyBillingTotal> = Vec::new();
+        for &tenant_id in tenant_ids {
+            let license = self.license_repo.get_by_tenant_id(tenant_id).await?;
+            let quotas = self.get_quota_usage_summary(tenant_id).await?.quotas;
+
+            let billing: Vec<CurrencyBillingTotal> = billing_totals
+                .iter()
+                .filter(|total| total.tenant_id == tenant_id)
+                .map(|total| CurrencyBillingTotal {
+                    currency: total.currency.clone(),
+                    billed: total.billed,
+                    paid: total.paid,
+                    outstanding: total.outstanding,
+                })
+                .collect();
+
+            for line in &billing {
+                match totals.iter_mut().find(|total| total.currency == line.currency) {
+                    Some(total) => {
+                        total.billed += line.billed;
+                        total.paid += line.paid;
+                        total.outstanding += line.outstanding;
+                    }
+                    None => totals.push(line.clone()),
+                }
+            }
+
+            tenants.push(TenantUsageRollup {
+                tenant_id,
+                subscription_tier: license.as_ref().map(|l| l.subscription_tier.clone()),
+                license_status: license.map(|l| l.status),
+                billing,
+                quotas,
+            });
+        }
+
+        Ok(UsageRollupReport {
+            period_start: start_date,
+            period_end: end_date,
+            tenants,
+            totals,
         })
     }
 
@@ -220,6 +1433,23 @@ impl LicenseService {
 
     // Workflow initiation methods
     pub async fn initiate_license_provisioning(&self, request: LicenseProvisioningWorkflowRequest) -> Result<String> {
+        // Refuse plans that can't be priced, and coupons the workflow would
+        // refuse, before starting it
+        let price = self.activities.quote_plan_price(QuotePlanPriceRequest {
+            tenant_id: request.tenant_id,
+            subscription_tier: request.subscription_tier.clone(),
+            billing_cycle: request.billing_cycle.clone(),
+        }).await?;
+        if let Some(coupon_code) = &request.coupon_code {
+            self.activities.validate_promotion(ValidatePromotionRequest {
+                tenant_id: request.tenant_id,
+                coupon_code: Some(coupon_code.clone()),
+                subscription_tier: request.subscription_tier.clone(),
+                amount: price.setup_fee,
+                currency: price.currency,
+            }).await?;
+        }
+
         // In a real implementation, this would start a Temporal workflow
         // For now, we'll return a mock workflow ID
         let workflow_id = format!("license_provisioning_{}", Uuid::new_v4());
@@ -258,6 +1488,65 @@ impl LicenseService {
         Ok(workflow_id)
     }
 
+    pub async fn initiate_subscription_change(&self, request: ChangeSubscriptionWorkflowRequest) -> Result<String> {
+        // Refuse changes the workflow would refuse before starting it
+        let preview = self.preview_subscription_change(SubscriptionChangeRequest {
+            tenant_id: request.tenant_id,
+            new_tier: request.new_tier.clone(),
+            new_billing_cycle: request.new_billing_cycle.clone(),
+        }).await?;
+        if let Some(reason) = preview.blocked_reason {
+            return Err(LicenseError::ValidationError(reason));
+        }
+        if let Some(expected) = request.expected_amount_due {
+            if expected != preview.amount_due {
+                return Err(LicenseError::ValidationError(format!(
+                    "Amount due changed since the preview: {} {} instead of {}",
+                    preview.amount_due, preview.currency, expected
+                )));
+            }
+        }
+
+        // In a real implementation, this would start a Temporal workflow
+        let workflow_id = format!("change_subscription_{}", Uuid::new_v4());
+        
+        tracing::info!("Initiated subscription change workflow: {}", workflow_id);
+        
+        // TODO: Start actual Temporal workflow
+        
+        Ok(workflow_id)
+    }
+
+    pub async fn initiate_dunning(&self, request: DunningWorkflowRequest) -> Result<String> {
+        let case = self.dunning_repo.get(request.case_id).await?
+            .ok_or_else(|| LicenseError::DunningCaseNotFound(request.case_id.to_string()))?;
+        if case.status != DunningStatus::Active {
+            return Err(LicenseError::ValidationError(format!(
+                "Dunning case {} is {:?}, not active", case.id, case.status
+            )));
+        }
+
+        // In a real implementation, this would start a Temporal workflow
+        let workflow_id = format!("dunning_{}", Uuid::new_v4());
+        
+        tracing::info!("Initiated dunning workflow: {}", workflow_id);
+        
+        // TODO: Start actual Temporal workflow
+        
+        Ok(workflow_id)
+    }
+
+    pub async fn initiate_seat_reconciliation(&self, request: SeatReconciliationWorkflowRequest) -> Result<String> {
+        // In a real implementation, this would start a Temporal workflow
+        let workflow_id = format!("seat_reconciliation_{}", Uuid::new_v4());
+        
+        tracing::info!("Initiated seat reconciliation workflow: {}", workflow_id);
+        
+        // TODO: Start actual Temporal workflow
+        
+        Ok(workflow_id)
+    }
+
     // Monitoring and analytics methods
     pub async fn get_license_analytics(&self, tenant_id: Uuid) -> Result<LicenseAnalytics> {
         let license = self.license_repo.get_by_tenant_id(tenant_id).await?
@@ -319,6 +1608,24 @@ pub struct QuotaUsageSummary {
     pub generated_at: DateTime<Utc>,
 }
 
+/// Usage and billing across a set of tenants, such as a reseller's customers
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct UsageRollupReport {
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub tenants: Vec<TenantUsageRollup>,
+    pub totals: Vec<CurrencyBillingTotal>,
+}
+
+#[derive(Debug, serde::Serialize, serde::Deserialize)]
+pub struct TenantUsageRollup {
+    pub tenant_id: Uuid,
+    pub subscription_tier: Option<SubscriptionTier>,
+    pub license_status: Option<LicenseStatus>,
+    pub billing: Vec<CurrencyBillingTotal>,
+    pub quotas: Vec<QuotaUsageItem>,
+}
+
 #[derive(Debug, serde::Serialize, serde::Deserialize)]
 pub struct QuotaUsageItem {
     pub quota_name: String,
@@ -343,14 +1650,3 @@ pub struct LicenseAnalytics {
     pub last_payment_date: Option<DateTime<Utc>>,
     pub auto_renew_enabled: bool,
 }
-
-// Helper trait for decimal parsing
-trait DecimalFromStr {
-    fn from_str(s: &str) -> Result<Decimal, rust_decimal::Error>;
-}
-
-impl DecimalFromStr for Decimal {
-    fn from_str(s: &str) -> Result<Decimal, rust_decimal::Error> {
-        s.parse()
-    }
-}
\ No newline at end of file
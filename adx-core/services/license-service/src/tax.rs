@@ -0,0 +1,665 @@
+use std::sync::Arc;
+
+use async_trait::async_trait;
+use rust_decimal::Decimal;
+use serde::Deserialize;
+
+use crate::{
+    config::{BillingConfig, StripeConfig, TaxConfig},
+    error::{LicenseError, Result},
+    models::{TaxBreakdown, TaxIdStatus, TenantTaxProfile},
+    pricing,
+    repositories::TaxRepository,
+};
+
+/// EU member states. Greek VAT numbers are prefixed "EL" rather than "GR".
+const EU_COUNTRIES: &[&str] = &[
+    "AT", "BE", "BG", "CY", "CZ", "DE", "DK", "EE", "ES", "FI", "FR", "GR", "HR", "HU",
+    "IE", "IT", "LT", "LU", "LV", "MT", "NL", "PL", "PT", "RO", "SE", "SI", "SK",
+];
+
+pub const REVERSE_CHARGE_NOTE: &str =
+    "Reverse charge: VAT to be accounted for by the recipient (Article 196, Council Directive 2006/112/EC)";
+pub const TAX_EXEMPT_NOTE: &str = "Customer is exempt from tax";
+
+/// TaxJar's product tax code for software as a service
+const TAXJAR_SAAS_TAX_CODE: &str = "30070";
+
+#[derive(Debug, Clone)]
+pub struct TaxAddress {
+    pub country: String,
+    pub region: Option<String>,
+    pub postal_code: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct TaxableLine {
+    /// Identifies the line to the provider; unique within a request
+    pub reference: String,
+    pub description: String,
+    pub amount: Decimal,
+}
+
+#[derive(Debug, Clone)]
+pub struct TaxCalculationRequest {
+    pub currency: String,
+    pub customer: TaxAddress,
+    pub lines: Vec<TaxableLine>,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct LineTax {
+    pub tax_amount: Decimal,
+    pub breakdown: Vec<TaxBreakdown>,
+}
+
+/// Tax of an invoice's lines, in the order they were given
+#[derive(Debug, Clone)]
+pub struct InvoiceTax {
+    pub provider: &'static str,
+    pub reverse_charge: bool,
+    pub note: Option<String>,
+    pub lines: Vec<LineTax>,
+}
+
+impl InvoiceTax {
+    pub fn total(&self) -> Decimal {
+        self.lines.iter().map(|line| line.tax_amount).sum()
+    }
+}
+
+#[async_trait]
+pub trait TaxProvider: Send + Sync {
+    /// Tax of each line of the request, in the same order
+    async fn calculate(&self, request: &TaxCalculationRequest) -> Result<Vec<LineTax>>;
+    fn name(&self) -> &'static str;
+}
+
+/// Works out invoice tax with the configured provider, and the cases no
+/// provider is asked about: tax-exempt customers and EU reverse charge
+#[derive(Clone)]
+pub struct TaxService {
+    provider: Arc<dyn TaxProvider>,
+    vies_client: ViesClient,
+    config: TaxConfig,
+}
+
+impl TaxService {
+    pub fn new(
+        config: &TaxConfig,
+        stripe_config: &StripeConfig,
+        billing_config: &BillingConfig,
+        tax_repo: TaxRepository,
+    ) -> Result<Self> {
+        let provider: Arc<dyn TaxProvider> = match config.provider.as_str() {
+            "manual" => Arc::new(ManualTaxProvider::new(tax_repo, billing_config)?),
+            "stripe" => Arc::new(StripeTaxProvider::new(stripe_config)),
+            "taxjar" => Arc::new(TaxJarProvider::new(config)),
+            other => return Err(LicenseError::ConfigError(format!("Unknown tax provider: {}", other))),
+        };
+
+        Ok(Self {
+            provider,
+            vies_client: ViesClient::new(&config.vies_url),
+            config: config.clone(),
+        })
+    }
+
+    /// Tax of an invoice to the tenant. Tenants without a tax profile are
+    /// taxed as customers in the seller's own country.
+    pub async fn calculate_invoice_tax(
+        &self,
+        profile: Option<&TenantTaxProfile>,
+        currency: &str,
+        lines: Vec<TaxableLine>,
+    ) -> Result<InvoiceTax> {
+        if profile.is_some_and(|profile| profile.tax_exempt) {
+            return Ok(self.untaxed(lines.len(), false, TAX_EXEMPT_NOTE));
+        }
+        if profile.is_some_and(|profile| reverse_charge_applies(&self.config.seller_country, profile)) {
+            return Ok(self.untaxed(lines.len(), true, REVERSE_CHARGE_NOTE));
+        }
+
+        let customer = match profile {
+            Some(profile) => TaxAddress {
+                country: profile.country.clone(),
+                region: profile.region.clone(),
+                postal_code: profile.postal_code.clone(),
+            },
+            None => self.seller_address(),
+        };
+        let line_count = lines.len();
+        let request = TaxCalculationRequest {
+            currency: currency.to_string(),
+            customer,
+            lines,
+        };
+
+        let taxes = self.provider.calculate(&request).await?;
+        if taxes.len() != line_count {
+            return Err(LicenseError::TaxError(format!(
+                "{} returned tax for {} of {} lines",
+                self.provider.name(),
+                taxes.len(),
+                line_count
+            )));
+        }
+
+        Ok(InvoiceTax {
+            provider: self.provider.name(),
+            reverse_charge: false,
+            note: None,
+            lines: taxes,
+        })
+    }
+
+    /// Check a VAT number with VIES. Numbers of countries outside the EU
+    /// can't be checked and are left unverified.
+    pub async fn check_tax_id(&self, tax_id: &str) -> Result<TaxIdStatus> {
+        match split_vat_number(tax_id) {
+            Some((country_code, number)) => {
+                if self.vies_client.check(&country_code, &number).await? {
+                    Ok(TaxIdStatus::Valid)
+                } else {
+                    Ok(TaxIdStatus::Invalid)
+                }
+            }
+            None => Ok(TaxIdStatus::Unverified),
+        }
+    }
+
+    pub fn provider_name(&self) -> &'static str {
+        self.provider.name()
+    }
+
+    fn untaxed(&self, line_count: usize, reverse_charge: bool, note: &str) -> InvoiceTax {
+        InvoiceTax {
+            provider: self.provider.name(),
+            reverse_charge,
+            note: Some(note.to_string()),
+            lines: vec![LineTax::default(); line_count],
+        }
+    }
+
+    fn seller_address(&self) -> TaxAddress {
+        TaxAddress {
+            country: self.config.seller_country.clone(),
+            region: non_empty(&self.config.seller_region),
+            postal_code: non_empty(&self.config.seller_postal_code),
+        }
+    }
+}
+
+/// Tax from the `tax_rates` tables, falling back to `billing.tax_rate` in
+/// countries without rates
+pub struct ManualTaxProvider {
+    tax_repo: TaxRepository,
+    default_rate: Decimal,
+}
+
+impl ManualTaxProvider {
+    pub fn new(tax_repo: TaxRepository, billing_config: &BillingConfig) -> Result<Self> {
+        let default_rate = Decimal::try_from(billing_config.tax_rate)
+            .map_err(|e| LicenseError::ConfigError(format!("Invalid tax rate: {}", e)))?;
+
+        Ok(Self {
+            tax_repo,
+            default_rate: default_rate * Decimal::ONE_HUNDRED,
+        })
+    }
+}
+
+#[async_trait]
+impl TaxProvider for ManualTaxProvider {
+    async fn calculate(&self, request: &TaxCalculationRequest) -> Result<Vec<LineTax>> {
+        let customer = &request.customer;
+        let mut rates: Vec<(String, String, Decimal)> = self.tax_repo
+            .get_applicable_rates(&customer.country, customer.region.as_deref())
+            .await?
+            .into_iter()
+            .map(|rate| {
+                let jurisdiction = match &rate.region {
+                    Some(region) => format!("{}-{}", rate.country, region),
+                    None => rate.country.clone(),
+                };
+                (rate.tax_type, jurisdiction, rate.rate)
+            })
+            .collect();
+        if rates.is_empty() && self.default_rate > Decimal::ZERO {
+            rates.push(("sales_tax".to_string(), customer.country.clone(), self.default_rate));
+        }
+
+        Ok(request.lines.iter()
+            .map(|line| {
+                let breakdown: Vec<TaxBreakdown> = rates.iter()
+                    .map(|(tax_type, jurisdiction, rate)| TaxBreakdown {
+                        tax_type: tax_type.clone(),
+                        jurisdiction: jurisdiction.clone(),
+                        rate: *rate,
+                        amount: (line.amount * rate / Decimal::ONE_HUNDRED).round_dp(2),
+                    })
+                    .collect();
+                LineTax {
+                    tax_amount: breakdown.iter().map(|tax| tax.amount).sum(),
+                    breakdown,
+                }
+            })
+            .collect())
+    }
+
+    fn name(&self) -> &'static str {
+        "manual"
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct StripeList<T> {
+    data: Vec<T>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StripeTaxCalculation {
+    line_items: StripeList<StripeTaxLine>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StripeTaxLine {
+    reference: String,
+    amount_tax: i64,
+    #[serde(default)]
+    tax_breakdown: Vec<StripeTaxBreakdown>,
+}
+
+#[derive(Debug, Deserialize)]
+struct StripeTaxBreakdown {
+    amount: i64,
+    jurisdiction: StripeTaxJurisdiction,
+    tax_rate_details: StripeTaxRateDetails,
+}
+
+#[derive(Debug, Deserialize)]
+struct StripeTaxJurisdiction {
+    display_name: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct StripeTaxRateDetails {
+    percentage_decimal: String,
+    tax_type: String,
+}
+
+/// Tax from Stripe Tax calculations
+pub struct StripeTaxProvider {
+    client: reqwest::Client,
+    secret_key: String,
+}
+
+impl StripeTaxProvider {
+    pub fn new(config: &StripeConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            secret_key: config.secret_key.clone(),
+        }
+    }
+}
+
+#[async_trait]
+impl TaxProvider for StripeTaxProvider {
+    async fn calculate(&self, request: &TaxCalculationRequest) -> Result<Vec<LineTax>> {
+        let customer = &request.customer;
+        let mut params: Vec<(String, String)> = vec![
+            ("currency".to_string(), request.currency.to_lowercase()),
+            ("customer_details[address][country]".to_string(), customer.country.clone()),
+            ("customer_details[address_source]".to_string(), "billing".to_string()),
+            ("expand[]".to_string(), "line_items.data.tax_breakdown".to_string()),
+        ];
+        if let Some(region) = &customer.region {
+            params.push(("customer_details[address][state]".to_string(), region.clone()));
+        }
+        if let Some(postal_code) = &customer.postal_code {
+            params.push(("customer_details[address][postal_code]".to_string(), postal_code.clone()));
+        }
+        for (i, line) in request.lines.iter().enumerate() {
+            params.push((format!("line_items[{}][amount]", i), pricing::to_minor_units(line.amount, &request.currency).to_string()));
+            params.push((format!("line_items[{}][reference]", i), line.reference.clone()));
+            params.push((format!("line_items[{}][tax_behavior]", i), "exclusive".to_string()));
+        }
+
+        let response = self.client
+            .post("https://api.stripe.com/v1/tax/calculations")
+            .header("Authorization", format!("Bearer {}", self.secret_key))
+            .form(&params)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(LicenseError::TaxError(format!("Stripe tax calculation failed: {}", error_text)));
+        }
+        let calculation: StripeTaxCalculation = response.json().await?;
+
+        request.lines.iter()
+            .map(|line| {
+                let taxed = calculation.line_items.data.iter()
+                    .find(|taxed| taxed.reference == line.reference)
+                    .ok_or_else(|| LicenseError::TaxError(format!("Stripe returned no tax for line {}", line.reference)))?;
+
+                let breakdown = taxed.tax_breakdown.iter()
+                    .map(|tax| TaxBreakdown {
+                        tax_type: tax.tax_rate_details.tax_type.clone(),
+                        jurisdiction: tax.jurisdiction.display_name.clone(),
+                        rate: tax.tax_rate_details.percentage_decimal.parse().unwrap_or_default(),
+                        amount: pricing::from_minor_units(tax.amount, &request.currency),
+                    })
+                    .collect();
+                Ok(LineTax {
+                    tax_amount: pricing::from_minor_units(taxed.amount_tax, &request.currency),
+                    breakdown,
+                })
+            })
+            .collect()
+    }
+
+    fn name(&self) -> &'static str {
+        "stripe"
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct TaxJarResponse {
+    tax: TaxJarTax,
+}
+
+#[derive(Debug, Deserialize)]
+struct TaxJarTax {
+    #[serde(default)]
+    breakdown: Option<TaxJarBreakdown>,
+    #[serde(default)]
+    jurisdictions: Option<TaxJarJurisdictions>,
+}
+
+#[derive(Debug, Deserialize)]
+struct TaxJarBreakdown {
+    #[serde(default)]
+    line_items: Vec<TaxJarLine>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TaxJarJurisdictions {
+    country: Option<String>,
+    state: Option<String>,
+    county: Option<String>,
+    city: Option<String>,
+}
+
+/// A line of TaxJar's breakdown. US lines are split by level; lines
+/// elsewhere carry a single country tax.
+#[derive(Debug, Deserialize)]
+struct TaxJarLine {
+    id: String,
+    tax_collectable: Decimal,
+    state_amount: Option<Decimal>,
+    state_sales_tax_rate: Option<Decimal>,
+    county_amount: Option<Decimal>,
+    county_tax_rate: Option<Decimal>,
+    city_amount: Option<Decimal>,
+    city_tax_rate: Option<Decimal>,
+    special_district_amount: Option<Decimal>,
+    special_tax_rate: Option<Decimal>,
+    country_tax_collectable: Option<Decimal>,
+    country_tax_rate: Option<Decimal>,
+}
+
+/// Tax from TaxJar's sales tax API
+pub struct TaxJarProvider {
+    client: reqwest::Client,
+    api_url: String,
+    api_key: String,
+    seller: TaxAddress,
+}
+
+impl TaxJarProvider {
+    pub fn new(config: &TaxConfig) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            api_url: config.taxjar_api_url.trim_end_matches('/').to_string(),
+            api_key: config.taxjar_api_key.clone(),
+            seller: TaxAddress {
+                country: config.seller_country.clone(),
+                region: non_empty(&config.seller_region),
+                postal_code: non_empty(&config.seller_postal_code),
+            },
+        }
+    }
+}
+
+#[async_trait]
+impl TaxProvider for TaxJarProvider {
+    async fn calculate(&self, request: &TaxCalculationRequest) -> Result<Vec<LineTax>> {
+        let customer = &request.customer;
+        let line_items: Vec<serde_json::Value> = request.lines.iter()
+            .map(|line| serde_json::json!({
+                "id": line.reference,
+                "quantity": 1,
+                "unit_price": line.amount,
+                "product_tax_code": TAXJAR_SAAS_TAX_CODE
+            }))
+            .collect();
+        let body = serde_json::json!({
+            "from_country": self.seller.country,
+            "from_state": self.seller.region,
+            "from_zip": self.seller.postal_code,
+            "to_country": customer.country,
+            "to_state": customer.region,
+            "to_zip": customer.postal_code,
+            "amount": request.lines.iter().map(|line| line.amount).sum::<Decimal>(),
+            "shipping": 0,
+            "line_items": line_items
+        });
+
+        let response = self.client
+            .post(&format!("{}/v2/taxes", self.api_url))
+            .header("Authorization", format!("Bearer {}", self.api_key))
+            .json(&body)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(LicenseError::TaxError(format!("TaxJar tax calculation failed: {}", error_text)));
+        }
+        let taxed: TaxJarResponse = response.json().await?;
+        let taxed_lines = taxed.tax.breakdown.map(|breakdown| breakdown.line_items).unwrap_or_default();
+        let jurisdictions = taxed.tax.jurisdictions.unwrap_or_default();
+        let country = jurisdictions.country.clone().unwrap_or_else(|| customer.country.clone());
+
+        Ok(request.lines.iter()
+            .map(|line| {
+                // TaxJar leaves untaxed lines out of its breakdown
+                let Some(taxed) = taxed_lines.iter().find(|taxed| taxed.id == line.reference) else {
+                    return LineTax::default();
+                };
+
+                let tax_type = if country == "US" { "sales_tax" } else { "vat" };
+                let levels = [
+                    (taxed.state_amount, taxed.state_sales_tax_rate, jurisdictions.state.as_ref()),
+                    (taxed.county_amount, taxed.county_tax_rate, jurisdictions.county.as_ref()),
+                    (taxed.city_amount, taxed.city_tax_rate, jurisdictions.city.as_ref()),
+                    (taxed.special_district_amount, taxed.special_tax_rate, None),
+                    (taxed.country_tax_collectable, taxed.country_tax_rate, Some(&country)),
+                ];
+                let breakdown = levels.into_iter()
+                    .filter_map(|(amount, rate, name)| {
+                        let amount = amount.filter(|amount| !amount.is_zero())?;
+                        Some(TaxBreakdown {
+                            tax_type: tax_type.to_string(),
+                            jurisdiction: name.cloned().unwrap_or_else(|| format!("{} special district", country)),
+                            rate: rate.unwrap_or_default() * Decimal::ONE_HUNDRED,
+                            amount,
+                        })
+                    })
+                    .collect();
+
+                LineTax {
+                    tax_amount: taxed.tax_collectable,
+                    breakdown,
+                }
+            })
+            .collect())
+    }
+
+    fn name(&self) -> &'static str {
+        "taxjar"
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ViesCheckResponse {
+    valid: bool,
+}
+
+/// The EU's VAT Information Exchange System, which confirms VAT numbers
+#[derive(Debug, Clone)]
+pub struct ViesClient {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl ViesClient {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Whether the number is a registered VAT number of the member state
+    pub async fn check(&self, country_code: &str, number: &str) -> Result<bool> {
+        let response = self.client
+            .post(&format!("{}/check-vat-number", self.base_url))
+            .json(&serde_json::json!({
+                "countryCode": country_code,
+                "vatNumber": number
+            }))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(LicenseError::TaxError(format!("VIES check of {}{} failed: {}", country_code, number, error_text)));
+        }
+
+        let checked: ViesCheckResponse = response.json().await?;
+        Ok(checked.valid)
+    }
+}
+
+/// Tax IDs are stored upper case, without spaces, dots or dashes
+pub fn normalize_tax_id(tax_id: &str) -> String {
+    tax_id.chars()
+        .filter(|c| !matches!(c, ' ' | '.' | '-'))
+        .collect::<String>()
+        .to_uppercase()
+}
+
+pub fn is_eu_country(country: &str) -> bool {
+    EU_COUNTRIES.contains(&country.to_uppercase().as_str())
+}
+
+/// The VIES country code and number of an EU VAT number, or None when the
+/// number has no EU prefix. Greek numbers written with the ISO "GR" prefix
+/// are checked under VIES's "EL".
+pub fn split_vat_number(tax_id: &str) -> Option<(String, String)> {
+    let tax_id = normalize_tax_id(tax_id);
+    if tax_id.len() < 4 || !tax_id.is_ascii() {
+        return None;
+    }
+    let (prefix, number) = tax_id.split_at(2);
+    let country = if prefix == "EL" { "GR" } else { prefix };
+    let vies_code = if country == "GR" { "EL" } else { prefix };
+    is_eu_country(country).then(|| (vies_code.to_string(), number.to_string()))
+}
+
+/// EU B2B sales across borders are reverse charged: the customer, whose VAT
+/// number has been confirmed, accounts for the VAT instead of the seller
+pub fn reverse_charge_applies(seller_country: &str, profile: &TenantTaxProfile) -> bool {
+    is_eu_country(seller_country)
+        && is_eu_country(&profile.country)
+        && !profile.country.eq_ignore_ascii_case(seller_country)
+        && profile.tax_id.is_some()
+        && profile.tax_id_status == TaxIdStatus::Valid
+}
+
+fn non_empty(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+    use uuid::Uuid;
+
+    fn profile(country: &str, tax_id: Option<&str>, tax_id_status: TaxIdStatus) -> TenantTaxProfile {
+        TenantTaxProfile {
+            tenant_id: Uuid::new_v4(),
+            legal_name: Some("Acme GmbH".to_string()),
+            country: country.to_string(),
+            region: None,
+            postal_code: None,
+            tax_id: tax_id.map(str::to_string),
+            tax_id_status,
+            tax_id_checked_at: None,
+            tax_exempt: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_split_vat_number() {
+        assert_eq!(split_vat_number("DE 123.456-789"), Some(("DE".to_string(), "123456789".to_string())));
+        assert_eq!(split_vat_number("fr40303265045"), Some(("FR".to_string(), "40303265045".to_string())));
+
+        // Greece is "EL" in VIES, whichever prefix the customer typed
+        assert_eq!(split_vat_number("EL094259216"), Some(("EL".to_string(), "094259216".to_string())));
+        assert_eq!(split_vat_number("GR094259216"), Some(("EL".to_string(), "094259216".to_string())));
+
+        // Not EU, or too short to hold a number
+        assert_eq!(split_vat_number("GB123456789"), None);
+        assert_eq!(split_vat_number("US12-3456789"), None);
+        assert_eq!(split_vat_number("DE1"), None);
+        assert_eq!(split_vat_number("DÉ123456789"), None);
+    }
+
+    #[test]
+    fn test_reverse_charge_for_cross_border_b2b() {
+        let german_customer = profile("DE", Some("DE123456789"), TaxIdStatus::Valid);
+        assert!(reverse_charge_applies("FR", &german_customer));
+        assert!(reverse_charge_applies("fr", &german_customer));
+
+        let greek_customer = profile("GR", Some("EL094259216"), TaxIdStatus::Valid);
+        assert!(reverse_charge_applies("DE", &greek_customer));
+    }
+
+    #[test]
+    fn test_no_reverse_charge_within_one_country() {
+        assert!(!reverse_charge_applies("DE", &profile("DE", Some("DE123456789"), TaxIdStatus::Valid)));
+        assert!(!reverse_charge_applies("GR", &profile("gr", Some("EL094259216"), TaxIdStatus::Valid)));
+    }
+
+    #[test]
+    fn test_no_reverse_charge_without_a_confirmed_vat_number() {
+        assert!(!reverse_charge_applies("FR", &profile("DE", None, TaxIdStatus::Valid)));
+        assert!(!reverse_charge_applies("FR", &profile("DE", Some("DE123456789"), TaxIdStatus::Unverified)));
+        assert!(!reverse_charge_applies("FR", &profile("DE", Some("DE123456789"), TaxIdStatus::Invalid)));
+    }
+
+    #[test]
+    fn test_no_reverse_charge_outside_the_eu() {
+        assert!(!reverse_charge_applies("US", &profile("DE", Some("DE123456789"), TaxIdStatus::Valid)));
+        assert!(!reverse_charge_applies("FR", &profile("GB", Some("GB123456789"), TaxIdStatus::Valid)));
+    }
+}
@@ -0,0 +1,117 @@
+use serde_json::json;
+use uuid::Uuid;
+
+use crate::{
+    error::{LicenseError, Result},
+    models::SubscriptionTier,
+};
+
+/// Tenant-service holds the tenant's plan, which its feature flags are
+/// evaluated against, so plan changes are mirrored there. Dunning also
+/// restricts features and suspends tenants through it.
+#[derive(Debug, Clone)]
+pub struct TenantServiceClient {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl TenantServiceClient {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    pub async fn update_plan(&self, tenant_id: Uuid, subscription_tier: &SubscriptionTier) -> Result<()> {
+        let response = self.client
+            .put(&format!("{}/api/v1/tenants/{}", self.base_url, tenant_id))
+            .header("X-Tenant-ID", tenant_id.to_string())
+            .json(&json!({ "subscription_tier": subscription_tier }))
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            let error_text = response.text().await?;
+            Err(LicenseError::Internal(format!("Tenant service plan update failed: {}", error_text)))
+        }
+    }
+
+    pub async fn get_admin_email(&self, tenant_id: Uuid) -> Result<String> {
+        let response = self.client
+            .get(&format!("{}/api/v1/tenants/{}", self.base_url, tenant_id))
+            .header("X-Tenant-ID", tenant_id.to_string())
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            let error_text = response.text().await?;
+            return Err(LicenseError::Internal(format!("Tenant service lookup failed: {}", error_text)));
+        }
+
+        let tenant: serde_json::Value = response.json().await?;
+        tenant["admin_email"].as_str()
+            .map(|email| email.to_string())
+            .ok_or_else(|| LicenseError::Internal(format!("Tenant {} has no admin email", tenant_id)))
+    }
+
+    /// Switch a feature flag off for the tenant, whatever its plan allows
+    pub async fn restrict_feature(&self, tenant_id: Uuid, feature: &str, reason: &str) -> Result<()> {
+        let response = self.client
+            .put(&format!("{}/api/v1/tenants/{}/feature-flags/{}", self.base_url, tenant_id, feature))
+            .header("X-Tenant-ID", tenant_id.to_string())
+            .json(&json!({ "enabled": false, "reason": reason }))
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            let error_text = response.text().await?;
+            Err(LicenseError::Internal(format!("Tenant service feature restriction failed: {}", error_text)))
+        }
+    }
+
+    /// Drop a restriction so the flag follows the tenant's plan again
+    pub async fn lift_feature_restriction(&self, tenant_id: Uuid, feature: &str) -> Result<()> {
+        let response = self.client
+            .delete(&format!("{}/api/v1/tenants/{}/feature-flags/{}", self.base_url, tenant_id, feature))
+            .header("X-Tenant-ID", tenant_id.to_string())
+            .send()
+            .await?;
+
+        // An override that is already gone has nothing to lift
+        if response.status().is_success() || response.status() == reqwest::StatusCode::NOT_FOUND {
+            Ok(())
+        } else {
+            let error_text = response.text().await?;
+            Err(LicenseError::Internal(format!("Tenant service feature restriction removal failed: {}", error_text)))
+        }
+    }
+
+    pub async fn suspend(&self, tenant_id: Uuid) -> Result<()> {
+        self.update_status(tenant_id, "Suspended").await
+    }
+
+    pub async fn reactivate(&self, tenant_id: Uuid) -> Result<()> {
+        self.update_status(tenant_id, "Active").await
+    }
+
+    async fn update_status(&self, tenant_id: Uuid, status: &str) -> Result<()> {
+        let response = self.client
+            .put(&format!("{}/api/v1/tenants/{}", self.base_url, tenant_id))
+            .header("X-Tenant-ID", tenant_id.to_string())
+            .json(&json!({ "status": status }))
+            .send()
+            .await?;
+
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            let error_text = response.text().await?;
+            Err(LicenseError::Internal(format!("Tenant service status update failed: {}", error_text)))
+        }
+    }
+}
@@ -20,6 +20,9 @@ pub struct LicenseProvisioningWorkflowRequest {
     pub features: Vec<String>,
     pub custom_quotas: Option<serde_json::Value>,
     pub setup_billing: bool,
+    /// Promotional code discounting the setup fee
+    #[serde(default)]
+    pub coupon_code: Option<String>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -30,6 +33,7 @@ pub struct LicenseProvisioningWorkflowResult {
     pub subscription_id: Option<String>,
     pub quotas_initialized: bool,
     pub billing_setup: bool,
+    pub coupon_redemption: Option<CouponRedemption>,
     pub status: LicenseStatus,
 }
 
@@ -74,16 +78,93 @@ pub struct LicenseRenewalWorkflowResult {
     pub notifications_sent: bool,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SeatReconciliationWorkflowRequest {
+    /// Tenants to reconcile; every tenant with a seat quota when not set
+    pub tenant_ids: Option<Vec<Uuid>>,
+    pub adjust_billing: bool,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct SeatReconciliationWorkflowResult {
+    pub tenants_checked: usize,
+    pub tenants_drifted: usize,
+    pub usage_corrections: usize,
+    pub billing_corrections: usize,
+    pub failed_tenants: Vec<Uuid>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangeSubscriptionWorkflowRequest {
+    pub tenant_id: Uuid,
+    pub new_tier: SubscriptionTier,
+    pub new_billing_cycle: Option<BillingCycle>,
+    /// Amount due the tenant confirmed from the preview; the change fails
+    /// if it no longer matches
+    pub expected_amount_due: Option<rust_decimal::Decimal>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ChangeSubscriptionWorkflowResult {
+    pub license_id: Uuid,
+    pub changed: bool,
+    pub amount_due: rust_decimal::Decimal,
+    pub payment_id: Option<String>,
+    pub invoice_number: Option<String>,
+    pub rolled_back: bool,
+    pub failure_reason: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DunningWorkflowRequest {
+    pub case_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DunningWorkflowResult {
+    pub case_id: Uuid,
+    pub status: DunningStatus,
+    pub attempt_count: i32,
+    pub next_attempt_at: Option<DateTime<Utc>>,
+    pub restricted_features: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BillingWebhookWorkflowRequest {
+    pub webhook_event_id: Uuid,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BillingWebhookWorkflowResult {
+    pub webhook_event_id: Uuid,
+    pub status: WebhookEventStatus,
+    pub error: Option<String>,
+}
+
+impl From<DunningCase> for DunningWorkflowResult {
+    fn from(case: DunningCase) -> Self {
+        Self {
+            case_id: case.id,
+            status: case.status,
+            attempt_count: case.attempt_count,
+            next_attempt_at: case.next_attempt_at,
+            restricted_features: case.restricted_features,
+        }
+    }
+}
+
 // Workflow implementations using shared temporal abstractions
 use adx_shared::{WorkflowContext, ActivityContext, WorkflowError, ActivityError};
 
 /// License Provisioning Workflow
 /// 
 /// This workflow handles the complete license provisioning process including:
+/// - Pricing the plan in the tenant's currency
+/// - Coupon validation
 /// - Customer creation in payment providers
 /// - License creation and activation
 /// - Quota initialization
-/// - Billing setup
+/// - Billing setup, with the coupon and tenant credit taken off the setup fee
 /// - Compliance logging
 pub async fn license_provisioning_workflow(
     request: LicenseProvisioningWorkflowRequest,
@@ -91,7 +172,35 @@ pub async fn license_provisioning_workflow(
 ) -> Result<LicenseProvisioningWorkflowResult> {
     tracing::info!("Starting license provisioning workflow for tenant: {}", request.tenant_id);
 
-    // Step 1: Provision the license
+    // Price the plan in the tenant's currency
+    let price: PlanPrice = execute_activity(
+        "quote_plan_price",
+        QuotePlanPriceRequest {
+            tenant_id: request.tenant_id,
+            subscription_tier: request.subscription_tier.clone(),
+            billing_cycle: request.billing_cycle.clone(),
+        },
+        ActivityContext::default(),
+    ).await.map_err(|e| LicenseError::WorkflowError(e))?;
+    let setup_fee = price.setup_fee;
+    let currency = price.currency.clone();
+
+    // Step 1: Validate the coupon before anything is provisioned
+    if let Some(coupon_code) = &request.coupon_code {
+        let _: PromotionQuote = execute_activity(
+            "validate_promotion",
+            ValidatePromotionRequest {
+                tenant_id: request.tenant_id,
+                coupon_code: Some(coupon_code.clone()),
+                subscription_tier: request.subscription_tier.clone(),
+                amount: setup_fee,
+                currency: currency.clone(),
+            },
+            ActivityContext::default(),
+        ).await.map_err(|e| LicenseError::WorkflowError(e))?;
+    }
+
+    // Step 2: Provision the license
     let provision_request = ProvisionLicenseRequest {
         tenant_id: request.tenant_id,
         subscription_tier: request.subscription_tier.clone(),
@@ -110,26 +219,70 @@ pub async fn license_provisioning_workflow(
         ActivityContext::default(),
     ).await.map_err(|e| LicenseError::WorkflowError(e))?;
 
-    // Step 2: Set up billing if requested
+    // Step 3: Set up billing if requested
+    let mut coupon_redemption: Option<CouponRedemption> = None;
     let billing_setup = if request.setup_billing && provision_result.customer_id.is_some() {
-        // Create initial invoice for setup
-        let invoice_request = ProcessPaymentRequest {
-            tenant_id: request.tenant_id,
-            amount: get_setup_fee(&request.subscription_tier),
-            currency: "USD".to_string(),
-            payment_method: request.payment_method.clone(),
-            customer_id: provision_result.customer_id.clone().unwrap(),
-            invoice_id: None,
+        // Redeem the coupon against the setup fee
+        let redeemed = match &request.coupon_code {
+            Some(coupon_code) => execute_activity(
+                "redeem_coupon",
+                RedeemCouponRequest {
+                    tenant_id: request.tenant_id,
+                    license_id: provision_result.license_id,
+                    coupon_code: coupon_code.clone(),
+                    subscription_tier: request.subscription_tier.clone(),
+                    amount: setup_fee,
+                    currency: currency.clone(),
+                },
+                ActivityContext::default(),
+            ).await.map(Some),
+            None => Ok(None),
         };
 
-        match execute_activity(
-            "process_payment",
-            invoice_request,
-            ActivityContext::default(),
-        ).await {
-            Ok(_) => true,
+        match redeemed {
+            Ok(redemption) => {
+                let discount = redemption.as_ref()
+                    .map(|r: &CouponRedemption| r.discount_amount)
+                    .unwrap_or_default();
+
+                // Create initial invoice for setup
+                let invoice_request = ProcessPaymentRequest {
+                    tenant_id: request.tenant_id,
+                    amount: setup_fee - discount,
+                    currency: currency.clone(),
+                    payment_method: request.payment_method.clone(),
+                    customer_id: provision_result.customer_id.clone().unwrap(),
+                    invoice_id: None,
+                };
+
+                match execute_activity::<_, crate::billing::PaymentResult>(
+                    "process_payment",
+                    invoice_request,
+                    ActivityContext::default(),
+                ).await {
+                    Ok(_) => {
+                        coupon_redemption = redemption;
+                        true
+                    }
+                    Err(e) => {
+                        tracing::warn!("Billing setup failed: {:?}", e);
+                        // The coupon wasn't used after all
+                        if let Some(redemption) = redemption {
+                            let _ = execute_activity::<_, ()>(
+                                "revert_coupon_redemption",
+                                RevertCouponRedemptionRequest {
+                                    redemption_id: redemption.id,
+                                    reason: format!("Setup fee payment failed: {:?}", e),
+                                },
+                                ActivityContext::default(),
+                            ).await;
+                        }
+                        false
+                    }
+                }
+            }
             Err(e) => {
-                tracing::warn!("Billing setup failed: {:?}", e);
+                tracing::warn!("Coupon redemption failed, setup fee not charged: {:?}", e);
                 false
             }
         }
@@ -137,7 +290,7 @@ pub async fn license_provisioning_workflow(
         true // No billing setup required
     };
 
-    // Step 3: Send welcome notification (if billing setup successful)
+    // Step 4: Send welcome notification (if billing setup successful)
     if billing_setup {
         let notification_request = SendWelcomeNotificationRequest {
             tenant_id: request.tenant_id,
@@ -161,6 +314,7 @@ pub async fn license_provisioning_workflow(
         subscription_id: provision_result.subscription_id,
         quotas_initialized: true,
         billing_setup,
+        coupon_redemption,
         status: provision_result.status,
     })
 }
@@ -420,6 +574,8 @@ pub async fn license_renewal_workflow(
                 unit_price: license_info.renewal_amount,
                 total_price: license_info.renewal_amount,
                 item_type: "subscription".to_string(),
+                tax_amount: rust_decimal::Decimal::ZERO,
+                tax_breakdown: Vec::new(),
             }],
         };
 
@@ -473,6 +629,324 @@ pub async fn license_renewal_workflow(
     })
 }
 
+/// Change Subscription Workflow
+/// 
+/// This workflow moves a tenant to another plan including:
+/// - Proration of the current period
+/// - License and quota limit changes
+/// - Plan update in tenant-service, which feature flags are gated on
+/// - Payment capture, rolling the changes back if it fails
+pub async fn change_subscription_workflow(
+    request: ChangeSubscriptionWorkflowRequest,
+    _context: WorkflowContext,
+) -> Result<ChangeSubscriptionWorkflowResult> {
+    tracing::info!("Starting subscription change workflow for tenant: {}", request.tenant_id);
+
+    // Step 1: Calculate the proration
+    let preview: ProrationPreview = execute_activity(
+        "preview_subscription_change",
+        SubscriptionChangeRequest {
+            tenant_id: request.tenant_id,
+            new_tier: request.new_tier.clone(),
+            new_billing_cycle: request.new_billing_cycle.clone(),
+        },
+        ActivityContext::default(),
+    ).await.map_err(|e| LicenseError::WorkflowError(e))?;
+
+    if let Some(reason) = preview.blocked_reason.clone() {
+        return Err(LicenseError::ValidationError(reason));
+    }
+    if let Some(expected) = request.expected_amount_due {
+        if expected != preview.amount_due {
+            return Err(LicenseError::ValidationError(format!(
+                "Amount due changed since the preview: {} {} instead of {}",
+                preview.amount_due, preview.currency, expected
+            )));
+        }
+    }
+
+    // Step 2: Change the license and quota limits
+    let applied: AppliedSubscriptionChange = execute_activity(
+        "apply_subscription_change",
+        ApplySubscriptionChangeRequest { preview: preview.clone() },
+        ActivityContext::default(),
+    ).await.map_err(|e| LicenseError::WorkflowError(e))?;
+
+    // Step 3: Update the plan in tenant-service so feature flags follow it
+    if let Err(e) = execute_activity::<_, ()>(
+        "update_tenant_plan",
+        UpdateTenantPlanRequest {
+            tenant_id: request.tenant_id,
+            subscription_tier: preview.new_tier.clone(),
+        },
+        ActivityContext::default(),
+    ).await {
+        tracing::error!("Tenant plan update failed: {:?}", e);
+        revert_subscription_change(&applied, false).await;
+        return Ok(rolled_back_result(&preview, format!("Tenant plan update failed: {:?}", e)));
+    }
+
+    // Step 4: Capture the prorated payment
+    let payment: CaptureProrationPaymentResult = match execute_activity(
+        "capture_proration_payment",
+        CaptureProrationPaymentRequest { preview: preview.clone() },
+        ActivityContext::default(),
+    ).await {
+        Ok(payment) => payment,
+        Err(e) => {
+            tracing::error!("Plan change payment failed: {:?}", e);
+            revert_subscription_change(&applied, true).await;
+
+            let compliance_request = LogComplianceEventRequest {
+                tenant_id: request.tenant_id,
+                event_type: "subscription_change_payment_failed".to_string(),
+                event_category: "billing".to_string(),
+                severity: "error".to_string(),
+                description: "Plan change payment failed and the change was rolled back".to_string(),
+                details: Some(serde_json::json!({
+                    "license_id": preview.license_id,
+                    "from_tier": preview.current_tier,
+                    "to_tier": preview.new_tier,
+                    "amount": preview.amount_due,
+                    "error": format!("{:?}", e)
+                })),
+                user_id: None,
+                resource_id: Some(preview.license_id),
+            };
+            let _ = execute_activity::<_, ()>(
+                "log_compliance_event",
+                compliance_request,
+                ActivityContext::default(),
+            ).await;
+
+            return Ok(rolled_back_result(&preview, format!("Payment failed: {:?}", e)));
+        }
+    };
+
+    // Step 5: Move the Stripe subscription to the new price (non-critical)
+    if let Err(e) = execute_activity::<_, ()>(
+        "update_stripe_subscription_plan",
+        UpdateTenantPlanRequest {
+            tenant_id: request.tenant_id,
+            subscription_tier: preview.new_tier.clone(),
+        },
+        ActivityContext::default(),
+    ).await {
+        tracing::warn!("Stripe subscription price update failed: {:?}", e);
+    }
+
+    // Step 6: Log compliance event
+    let compliance_request = LogComplianceEventRequest {
+        tenant_id: request.tenant_id,
+        event_type: "subscription_changed".to_string(),
+        event_category: "license".to_string(),
+        severity: "info".to_string(),
+        description: format!("Plan changed from {:?} to {:?}", preview.current_tier, preview.new_tier),
+        details: Some(serde_json::json!({
+            "license_id": preview.license_id,
+            "billing_cycle": preview.new_billing_cycle,
+            "amount_due": preview.amount_due,
+            "payment_id": payment.payment.as_ref().map(|p| p.payment_id.clone()),
+            "quota_changes": preview.quota_changes
+        })),
+        user_id: None,
+        resource_id: Some(preview.license_id),
+    };
+    let _ = execute_activity::<_, ()>(
+        "log_compliance_event",
+        compliance_request,
+        ActivityContext::default(),
+    ).await;
+
+    Ok(ChangeSubscriptionWorkflowResult {
+        license_id: preview.license_id,
+        changed: true,
+        amount_due: preview.amount_due,
+        payment_id: payment.payment.map(|p| p.payment_id),
+        invoice_number: payment.invoice_number,
+        rolled_back: false,
+        failure_reason: None,
+    })
+}
+
+/// Seat Reconciliation Workflow
+/// 
+/// This workflow runs nightly and brings seat counts back in line:
+/// - Seat quota usage is reset to the number of assigned seats
+/// - Stripe subscription quantities are adjusted to the assigned seats
+/// - Drift is logged as a compliance event
+pub async fn seat_reconciliation_workflow(
+    request: SeatReconciliationWorkflowRequest,
+    _context: WorkflowContext,
+) -> Result<SeatReconciliationWorkflowResult> {
+    tracing::info!("Starting seat reconciliation workflow");
+
+    // Step 1: Find the tenants to reconcile
+    let tenant_ids: Vec<Uuid> = match request.tenant_ids {
+        Some(tenant_ids) => tenant_ids,
+        None => execute_activity(
+            "list_seat_tenants",
+            ListSeatTenantsRequest {},
+            ActivityContext::default(),
+        ).await.map_err(|e| LicenseError::WorkflowError(e))?,
+    };
+
+    // Step 2: Reconcile each tenant; one tenant failing does not stop the rest
+    let mut result = SeatReconciliationWorkflowResult::default();
+    for tenant_id in tenant_ids {
+        let reconcile_request = ReconcileTenantSeatsRequest {
+            tenant_id,
+            adjust_billing: request.adjust_billing,
+        };
+
+        match execute_activity::<_, SeatReconciliation>(
+            "reconcile_tenant_seats",
+            reconcile_request,
+            ActivityContext::default(),
+        ).await {
+            Ok(reconciliation) => {
+                result.tenants_checked += 1;
+                if reconciliation.drifted() {
+                    result.tenants_drifted += 1;
+                }
+                if reconciliation.usage_corrected {
+                    result.usage_corrections += 1;
+                }
+                if reconciliation.billing_corrected {
+                    result.billing_corrections += 1;
+                }
+                if reconciliation.error.is_some() {
+                    result.failed_tenants.push(tenant_id);
+                }
+            }
+            Err(e) => {
+                tracing::warn!("Seat reconciliation failed for tenant {}: {:?}", tenant_id, e);
+                result.failed_tenants.push(tenant_id);
+            }
+        }
+    }
+
+    tracing::info!(
+        "Seat reconciliation checked {} tenants: {} drifted, {} failed",
+        result.tenants_checked,
+        result.tenants_drifted,
+        result.failed_tenants.len()
+    );
+
+    Ok(result)
+}
+
+/// Dunning Workflow
+/// 
+/// This workflow runs a due retry of a dunning case:
+/// - The failed invoice's charge is retried
+/// - A paid invoice closes the case and lifts its restrictions
+/// - A failed retry restricts more features and schedules the next retry
+/// - The tenant is suspended through tenant-service once the last retry fails
+pub async fn dunning_workflow(
+    request: DunningWorkflowRequest,
+    _context: WorkflowContext,
+) -> Result<DunningWorkflowResult> {
+    tracing::info!("Starting dunning workflow for case: {}", request.case_id);
+
+    // Step 1: Skip cases paused or closed since they were picked up
+    let case: DunningCase = execute_activity(
+        "get_dunning_case",
+        DunningCaseRequest { case_id: request.case_id },
+        ActivityContext::default(),
+    ).await.map_err(|e| LicenseError::WorkflowError(e))?;
+    if case.status != DunningStatus::Active {
+        return Ok(case.into());
+    }
+
+    // Step 2: Retry the charge
+    let charge: DunningChargeResult = execute_activity(
+        "retry_dunning_charge",
+        DunningCaseRequest { case_id: case.id },
+        ActivityContext::default(),
+    ).await.map_err(|e| LicenseError::WorkflowError(e))?;
+
+    // Step 3: Close the case, escalate it or suspend the tenant
+    let case: DunningCase = if charge.paid {
+        execute_activity(
+            "resolve_dunning_case",
+            ResolveDunningCaseRequest {
+                case_id: case.id,
+                status: DunningStatus::Recovered,
+                resolved_by: None,
+                notes: Some("Paid on retry".to_string()),
+            },
+            ActivityContext::default(),
+        ).await.map_err(|e| LicenseError::WorkflowError(e))?
+    } else if charge.case.next_attempt_at.is_some() {
+        execute_activity(
+            "restrict_dunning_features",
+            DunningCaseRequest { case_id: case.id },
+            ActivityContext::default(),
+        ).await.map_err(|e| LicenseError::WorkflowError(e))?
+    } else {
+        execute_activity(
+            "suspend_dunning_tenant",
+            DunningCaseRequest { case_id: case.id },
+            ActivityContext::default(),
+        ).await.map_err(|e| LicenseError::WorkflowError(e))?
+    };
+
+    tracing::info!(
+        "Dunning case {} is {:?} after {} retries",
+        case.id,
+        case.status,
+        case.attempt_count
+    );
+
+    Ok(case.into())
+}
+
+/// Billing Webhook Workflow
+/// 
+/// This workflow applies a recorded Stripe or PayPal event:
+/// - License status, renewal and expiry follow subscription events
+/// - Payments and refunds are recorded in billing history
+/// - Failed and paid invoices open and close dunning cases
+/// - The event is marked processed, ignored or failed; failed events are
+///   applied again when the provider redelivers them
+pub async fn billing_webhook_workflow(
+    request: BillingWebhookWorkflowRequest,
+    _context: WorkflowContext,
+) -> Result<BillingWebhookWorkflowResult> {
+    tracing::info!("Starting billing webhook workflow for event: {}", request.webhook_event_id);
+
+    // Step 1: Apply the event
+    let (status, error) = match execute_activity::<_, WebhookEventStatus>(
+        "apply_billing_event",
+        WebhookEventRequest { webhook_event_id: request.webhook_event_id },
+        ActivityContext::default(),
+    ).await {
+        Ok(status) => (status, None),
+        Err(e) => {
+            tracing::warn!("Failed to apply webhook event {}: {:?}", request.webhook_event_id, e);
+            (WebhookEventStatus::Failed, Some(e.to_string()))
+        }
+    };
+
+    // Step 2: Record the outcome
+    execute_activity::<_, ()>(
+        "mark_webhook_event",
+        MarkWebhookEventRequest {
+            webhook_event_id: request.webhook_event_id,
+            status: status.clone(),
+            error: error.clone(),
+        },
+        ActivityContext::default(),
+    ).await.map_err(|e| LicenseError::WorkflowError(e))?;
+
+    Ok(BillingWebhookWorkflowResult {
+        webhook_event_id: request.webhook_event_id,
+        status,
+        error,
+    })
+}
+
 // Helper functions and additional request types
 #[derive(Debug, Serialize, Deserialize)]
 pub struct SendWelcomeNotificationRequest {
@@ -512,6 +986,9 @@ pub struct LogComplianceEventRequest {
     pub resource_id: Option<Uuid>,
 }
 
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ListSeatTenantsRequest {}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct GetLicenseInfoRequest {
     pub license_id: Uuid,
@@ -548,14 +1025,39 @@ pub struct SendRenewalNotificationRequest {
 }
 
 // Helper functions
-fn get_setup_fee(tier: &SubscriptionTier) -> rust_decimal::Decimal {
-    use rust_decimal_macros::dec;
-    
-    match tier {
-        SubscriptionTier::Free => dec!(0.00),
-        SubscriptionTier::Professional => dec!(0.00), // No setup fee
-        SubscriptionTier::Enterprise => dec!(99.00),
-        SubscriptionTier::Custom => dec!(0.00),
+/// Undo a plan change's license, quota and, if it got that far, tenant plan updates
+async fn revert_subscription_change(applied: &AppliedSubscriptionChange, revert_tenant_plan: bool) {
+    if revert_tenant_plan {
+        if let Err(e) = execute_activity::<_, ()>(
+            "update_tenant_plan",
+            UpdateTenantPlanRequest {
+                tenant_id: applied.tenant_id,
+                subscription_tier: applied.previous_tier.clone(),
+            },
+            ActivityContext::default(),
+        ).await {
+            tracing::error!("Reverting tenant plan failed: {:?}", e);
+        }
+    }
+
+    if let Err(e) = execute_activity::<_, ()>(
+        "revert_subscription_change",
+        applied.clone(),
+        ActivityContext::default(),
+    ).await {
+        tracing::error!("Reverting subscription change failed: {:?}", e);
+    }
+}
+
+fn rolled_back_result(preview: &ProrationPreview, failure_reason: String) -> ChangeSubscriptionWorkflowResult {
+    ChangeSubscriptionWorkflowResult {
+        license_id: preview.license_id,
+        changed: false,
+        amount_due: preview.amount_due,
+        payment_id: None,
+        invoice_number: None,
+        rolled_back: true,
+        failure_reason: Some(failure_reason),
     }
 }
 
@@ -6,7 +6,7 @@ use tracing::{info, warn, error};
 use crate::{
     ModuleResult, ModuleError, ModuleRepository, ModuleSandbox, ModuleSecurityScanner,
     ModuleMarketplace, ModulePackage, ModuleInstance, ModuleStatus, SecurityScanResult,
-    workflows::*,
+    PackageVerifier, LifecycleHookRunner, HookRun, workflows::*,
 };
 
 /// Module activities implementation for Temporal workflows
@@ -15,6 +15,8 @@ pub struct ModuleActivities {
     marketplace: Arc<dyn ModuleMarketplace>,
     sandbox: Arc<dyn ModuleSandbox>,
     security_scanner: Arc<dyn ModuleSecurityScanner>,
+    verifier: Arc<PackageVerifier>,
+    hooks: Arc<LifecycleHookRunner>,
     dependency_resolver: Arc<DependencyResolver>,
     notification_service: Arc<NotificationService>,
 }
@@ -25,12 +27,16 @@ impl ModuleActivities {
         marketplace: Arc<dyn ModuleMarketplace>,
         sandbox: Arc<dyn ModuleSandbox>,
         security_scanner: Arc<dyn ModuleSecurityScanner>,
+        verifier: Arc<PackageVerifier>,
+        hooks: Arc<LifecycleHookRunner>,
     ) -> Self {
         Self {
             repository,
             marketplace,
             sandbox,
             security_scanner,
+            verifier,
+            hooks,
             dependency_resolver: Arc::new(DependencyResolver::new()),
             notification_service: Arc::new(NotificationService::new()),
         }
@@ -486,6 +492,28 @@ impl ModuleActivities {
         })
     }
 
+    /// Run a module's lifecycle hook in its sandbox; a hook that fails has
+    /// compensated itself
+    #[temporal_sdk::activity]
+    pub async fn run_lifecycle_hook(
+        &self,
+        request: LifecycleHookRequest,
+    ) -> ModuleResult<Option<HookRun>> {
+        info!("Running {} hook for module: {}", request.context.phase.as_str(), request.context.instance_id);
+        self.hooks.run(&request.manifest, &request.context).await
+    }
+
+    /// Undo a lifecycle hook after a later step of the workflow failed
+    #[temporal_sdk::activity]
+    pub async fn compensate_lifecycle_hook(
+        &self,
+        request: LifecycleHookRequest,
+    ) -> ModuleResult<()> {
+        info!("Compensating {} hook for module: {}", request.context.phase.as_str(), request.context.instance_id);
+        self.hooks.compensate(&request.manifest, &request.context).await;
+        Ok(())
+    }
+
     // Helper methods
 
     async fn check_tenant_permissions(&self, tenant_id: &str, module_id: &str) -> ModuleResult<bool> {
@@ -501,9 +529,7 @@ impl ModuleActivities {
     }
 
     async fn verify_package_integrity(&self, package: &ModulePackage) -> ModuleResult<()> {
-        // Verify package checksum and signature
-        // Implementation would validate the package integrity
-        Ok(())
+        self.verifier.verify(package).await
     }
 
     async fn deploy_module_files(&self, package: &ModulePackage, path: &str) -> ModuleResult<()> {
@@ -0,0 +1,273 @@
+use std::collections::HashSet;
+use std::sync::Arc;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::{DateTime, Utc};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha384};
+use uuid::Uuid;
+
+use crate::{ModuleResult, ModuleError, ModuleManifest, ModulePackage, ModuleRepository, ModuleStatus, MicroFrontend};
+use crate::signing::package_checksum;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct AssetConfig {
+    /// Path assets are served under, as the shell reaches it through the
+    /// API gateway
+    pub public_path: String,
+    pub max_asset_bytes: u64,
+    pub max_package_asset_bytes: u64,
+    /// Max-age of served assets; a version's assets never change
+    pub cache_max_age_seconds: u64,
+}
+
+impl Default for AssetConfig {
+    fn default() -> Self {
+        Self {
+            public_path: "/api/v1/modules/assets".to_string(),
+            max_asset_bytes: 5 * 1024 * 1024,
+            max_package_asset_bytes: 50 * 1024 * 1024,
+            cache_max_age_seconds: 365 * 24 * 3600,
+        }
+    }
+}
+
+/// A frontend asset of a module version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleAsset {
+    pub module_id: String,
+    pub version: Version,
+    pub path: String,
+    pub content_type: String,
+    /// Hex SHA-256 of the content
+    pub etag: String,
+    pub size_bytes: u64,
+    #[serde(skip)]
+    pub content: Vec<u8>,
+}
+
+/// The remote entry and micro-frontends of a module version
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrontendBundle {
+    pub module_id: String,
+    pub version: Version,
+    /// Asset path of the remote entry
+    pub remote_entry: String,
+    /// Subresource integrity of the remote entry
+    pub integrity: String,
+    pub micro_frontends: Vec<MicroFrontend>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// The micro-frontends a tenant's shell mounts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrontendManifest {
+    pub tenant_id: String,
+    pub modules: Vec<FrontendModule>,
+    pub generated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FrontendModule {
+    pub instance_id: Uuid,
+    pub module_id: String,
+    pub version: Version,
+    /// Module federation container name of the remote entry
+    pub scope: String,
+    pub remote_entry_url: String,
+    pub integrity: String,
+    pub micro_frontends: Vec<MicroFrontend>,
+}
+
+/// Stores the frontend assets modules bundle, per module version, and
+/// builds the manifest the shell mounts a tenant's micro-frontends from
+pub struct ModuleAssetStore {
+    config: AssetConfig,
+    repository: Arc<dyn ModuleRepository>,
+}
+
+impl ModuleAssetStore {
+    pub fn new(config: AssetConfig, repository: Arc<dyn ModuleRepository>) -> Self {
+        Self { config, repository }
+    }
+
+    /// Store a verified package's assets. Assets of a version that is
+    /// already stored are left alone.
+    pub async fn store_package(&self, package: &ModulePackage) -> ModuleResult<()> {
+        self.validate_package(package)?;
+        if package.assets.is_empty() {
+            return Ok(());
+        }
+
+        let assets: Vec<ModuleAsset> = package.assets.iter()
+            .map(|asset| ModuleAsset {
+                module_id: package.metadata.id.clone(),
+                version: package.metadata.version.clone(),
+                path: asset.path.clone(),
+                content_type: asset.content_type.clone(),
+                etag: package_checksum(&asset.content),
+                size_bytes: asset.content.len() as u64,
+                content: asset.content.clone(),
+            })
+            .collect();
+
+        let bundle = remote_entry_path(&package.manifest)
+            .and_then(|entry| package.assets.iter().find(|asset| asset.path == entry))
+            .map(|entry| FrontendBundle {
+                module_id: package.metadata.id.clone(),
+                version: package.metadata.version.clone(),
+                remote_entry: entry.path.clone(),
+                integrity: integrity(&entry.content),
+                micro_frontends: package.manifest.micro_frontends.clone(),
+                created_at: Utc::now(),
+            });
+
+        self.repository.save_module_assets(bundle.as_ref(), &assets).await
+    }
+
+    /// Check a package's asset paths and sizes, and that the micro-frontends
+    /// it declares have a remote entry to load from
+    pub fn validate_package(&self, package: &ModulePackage) -> ModuleResult<()> {
+        let name = format!("{} {}", package.metadata.id, package.metadata.version);
+
+        let mut paths = HashSet::new();
+        let mut total_bytes = 0;
+        for asset in &package.assets {
+            check_asset_path(&asset.path)?;
+            if !paths.insert(asset.path.as_str()) {
+                return Err(ModuleError::ValidationFailed(format!("{} bundles asset {} twice", name, asset.path)));
+            }
+            if asset.content.len() as u64 > self.config.max_asset_bytes {
+                return Err(ModuleError::ValidationFailed(format!(
+                    "Asset {} of {} is larger than {} bytes", asset.path, name, self.config.max_asset_bytes
+                )));
+            }
+            total_bytes += asset.content.len() as u64;
+        }
+        if total_bytes > self.config.max_package_asset_bytes {
+            return Err(ModuleError::ValidationFailed(format!(
+                "Assets of {} are larger than {} bytes", name, self.config.max_package_asset_bytes
+            )));
+        }
+
+        if package.manifest.micro_frontends.is_empty() {
+            return Ok(());
+        }
+        let entry = remote_entry_path(&package.manifest)
+            .ok_or_else(|| ModuleError::ValidationFailed(
+                format!("{} declares micro-frontends but no frontend entry", name)
+            ))?;
+        if !paths.contains(entry.as_str()) {
+            return Err(ModuleError::ValidationFailed(
+                format!("Frontend entry {} of {} is not among its assets", entry, name)
+            ));
+        }
+
+        let mut names = HashSet::new();
+        for micro_frontend in &package.manifest.micro_frontends {
+            if !names.insert(micro_frontend.name.as_str()) {
+                return Err(ModuleError::ValidationFailed(
+                    format!("{} declares micro-frontend {} twice", name, micro_frontend.name)
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    pub async fn get_asset(&self, module_id: &str, version: &str, path: &str) -> ModuleResult<ModuleAsset> {
+        let not_found = || ModuleError::NotFound(format!("Asset {} of {} {}", path, module_id, version));
+        let version = Version::parse(version).map_err(|_| not_found())?;
+        self.repository.get_module_asset(module_id, &version, path).await?
+            .ok_or_else(not_found)
+    }
+
+    /// Cache-Control of served assets. Asset URLs carry the module version,
+    /// so browsers and the gateway may keep them for good.
+    pub fn cache_control(&self) -> String {
+        format!("public, max-age={}, immutable", self.config.cache_max_age_seconds)
+    }
+
+    /// The micro-frontends of a tenant's active modules
+    pub async fn frontend_manifest(&self, tenant_id: &str) -> ModuleResult<FrontendManifest> {
+        let mut modules = Vec::new();
+        for instance in self.repository.list_tenant_instances(tenant_id).await? {
+            if !matches!(instance.status, ModuleStatus::Active) {
+                continue;
+            }
+            let Some(bundle) = self.repository.get_frontend_bundle(&instance.module_id, &instance.version).await? else {
+                continue;
+            };
+            if bundle.micro_frontends.is_empty() {
+                continue;
+            }
+
+            modules.push(FrontendModule {
+                instance_id: instance.id,
+                scope: instance.module_id.replace('-', "_"),
+                remote_entry_url: self.asset_url(&bundle.module_id, &bundle.version, &bundle.remote_entry),
+                integrity: bundle.integrity,
+                micro_frontends: bundle.micro_frontends,
+                module_id: bundle.module_id,
+                version: bundle.version,
+            });
+        }
+        modules.sort_by(|a, b| a.module_id.cmp(&b.module_id));
+
+        Ok(FrontendManifest {
+            tenant_id: tenant_id.to_string(),
+            modules,
+            generated_at: Utc::now(),
+        })
+    }
+
+    fn asset_url(&self, module_id: &str, version: &Version, path: &str) -> String {
+        format!("{}/{}/{}/{}", self.config.public_path.trim_end_matches('/'), module_id, version, path)
+    }
+}
+
+/// Asset path of the manifest's frontend entry: assets are bundled from the
+/// entry's directory, so it is the entry's file name
+pub fn remote_entry_path(manifest: &ModuleManifest) -> Option<String> {
+    let entry = manifest.extension_points.frontend_entry.as_deref()?;
+    entry.rsplit('/').next()
+        .filter(|name| !name.is_empty())
+        .map(str::to_string)
+}
+
+/// Content type of an asset by its extension
+pub fn content_type_for(path: &str) -> &'static str {
+    let extension = path.rsplit_once('.').map(|(_, extension)| extension.to_ascii_lowercase());
+    match extension.as_deref() {
+        Some("js") | Some("mjs") => "text/javascript",
+        Some("css") => "text/css",
+        Some("html") => "text/html",
+        Some("json") | Some("map") => "application/json",
+        Some("svg") => "image/svg+xml",
+        Some("png") => "image/png",
+        Some("jpg") | Some("jpeg") => "image/jpeg",
+        Some("gif") => "image/gif",
+        Some("webp") => "image/webp",
+        Some("ico") => "image/x-icon",
+        Some("woff") => "font/woff",
+        Some("woff2") => "font/woff2",
+        Some("wasm") => "application/wasm",
+        _ => "application/octet-stream",
+    }
+}
+
+fn check_asset_path(path: &str) -> ModuleResult<()> {
+    let valid = !path.is_empty()
+        && !path.starts_with('/')
+        && !path.contains('\\')
+        && path.split('/').all(|segment| !segment.is_empty() && segment != "." && segment != "..");
+    if !valid {
+        return Err(ModuleError::ValidationFailed(format!("Invalid asset path {}", path)));
+    }
+    Ok(())
+}
+
+/// Subresource integrity of an asset, as the shell checks it when loading
+fn integrity(content: &[u8]) -> String {
+    format!("sha384-{}", BASE64.encode(Sha384::digest(content)))
+}
@@ -0,0 +1,199 @@
+use std::path::{Path, PathBuf};
+use clap::{Parser, Subcommand};
+use serde_json::Value;
+
+use module_service::sdk::cli::{self, ScaffoldOptions};
+use module_service::{ModuleResult, ModuleError, ModuleTestHarness, PackageSigner};
+
+#[derive(Parser)]
+#[command(name = "adx-module")]
+#[command(about = "ADX Core module developer tools - scaffold, run, validate, package and publish modules")]
+struct Cli {
+    #[command(subcommand)]
+    command: Commands,
+}
+
+#[derive(Subcommand)]
+enum Commands {
+    /// Create a new Rust module
+    New {
+        /// Module id, e.g. invoice-sync
+        module_id: String,
+        #[arg(long)]
+        name: Option<String>,
+        #[arg(long, default_value = "Module Developer")]
+        author: String,
+        /// Directory to create; defaults to the module id
+        #[arg(long)]
+        dir: Option<PathBuf>,
+        /// Path of the module-service crate, relative to the new module
+        #[arg(long, default_value = "../adx-core/services/module-service")]
+        sdk_path: String,
+    },
+    /// Check a module's manifest
+    Validate {
+        #[arg(long, default_value = ".")]
+        dir: PathBuf,
+    },
+    /// Run a built module against a local mock of the host until interrupted
+    Run {
+        #[arg(long, default_value = ".")]
+        dir: PathBuf,
+        /// JSON file with settings over the manifest's defaults
+        #[arg(long)]
+        config: Option<PathBuf>,
+        /// Platform event to send once started, as TYPE=JSON
+        #[arg(long = "event")]
+        events: Vec<String>,
+    },
+    /// Package a built module into dist/
+    Package {
+        #[arg(long, default_value = ".")]
+        dir: PathBuf,
+        /// PKCS#8 Ed25519 key to sign the package with
+        #[arg(long, requires = "key_id")]
+        key: Option<PathBuf>,
+        /// Id the signing key is registered under
+        #[arg(long)]
+        key_id: Option<String>,
+    },
+    /// Submit a signed package for marketplace review
+    Publish {
+        package: PathBuf,
+        /// Documentation submitted with the package
+        #[arg(long, default_value = "README.md")]
+        readme: PathBuf,
+        #[arg(long, env = "ADX_MARKETPLACE_URL", default_value = "https://marketplace.adxcore.com")]
+        marketplace_url: String,
+        #[arg(long, env = "ADX_MARKETPLACE_API_KEY")]
+        api_key: String,
+    },
+    /// Generate a package signing key and print the public key to register
+    Keygen {
+        #[arg(long)]
+        key_id: String,
+        #[arg(long, default_value = "signing-key.pk8")]
+        out: PathBuf,
+    },
+}
+
+#[tokio::main]
+async fn main() {
+    tracing_subscriber::fmt::init();
+
+    if let Err(e) = run(Cli::parse().command).await {
+        eprintln!("error: {}", e);
+        std::process::exit(1);
+    }
+}
+
+async fn run(command: Commands) -> ModuleResult<()> {
+    match command {
+        Commands::New { module_id, name, author, dir, sdk_path } => {
+            let directory = cli::scaffold(&ScaffoldOptions {
+                name: name.unwrap_or_else(|| module_id.clone()),
+                directory: dir.unwrap_or_else(|| PathBuf::from(&module_id)),
+                module_id,
+                author,
+                sdk_path,
+            })?;
+            println!("Created module in {}", directory.display());
+            println!("Next: cd {} && cargo test", directory.display());
+            Ok(())
+        }
+        Commands::Validate { dir } => {
+            let report = cli::validate_manifest(&cli::load_manifest(&dir)?);
+            for warning in &report.warnings {
+                println!("warning: {}", warning);
+            }
+            for error in &report.errors {
+                println!("error: {}", error);
+            }
+            if !report.is_valid() {
+                return Err(ModuleError::ValidationFailed(format!("{} errors in the manifest", report.errors.len())));
+            }
+            println!("Manifest is valid");
+            Ok(())
+        }
+        Commands::Run { dir, config, events } => run_local(&dir, config.as_deref(), &events).await,
+        Commands::Package { dir, key, key_id } => {
+            let signer = match (key, key_id) {
+                (Some(key), Some(key_id)) => Some(PackageSigner::from_pkcs8(&key_id, &std::fs::read(key)?)?),
+                _ => None,
+            };
+            let (package, path) = cli::package(&dir, signer.as_ref())?;
+            println!("Packaged {} {} into {}", package.metadata.id, package.metadata.version, path.display());
+            println!("Checksum: {}", package.checksum);
+            if !package.assets.is_empty() {
+                println!("Bundled {} frontend assets", package.assets.len());
+            }
+            if package.signature.is_none() {
+                println!("warning: the package is not signed; the marketplace will not accept it");
+            }
+            Ok(())
+        }
+        Commands::Publish { package, readme, marketplace_url, api_key } => {
+            let package = cli::read_package(&package)?;
+            let documentation = std::fs::read_to_string(&readme).unwrap_or_default();
+            let result = cli::publish(&package, documentation, &marketplace_url, &api_key).await?;
+            println!("Submitted {} {} for review: {}", package.metadata.id, package.metadata.version, result.submission_id);
+            println!("Review timeline: {}", result.review_timeline);
+            for requirement in &result.requirements {
+                println!("- {}", requirement);
+            }
+            Ok(())
+        }
+        Commands::Keygen { key_id, out } => {
+            let pkcs8 = PackageSigner::generate_pkcs8()?;
+            let signer = PackageSigner::from_pkcs8(&key_id, &pkcs8)?;
+            std::fs::write(&out, &pkcs8)?;
+            println!("Wrote signing key {} to {}", key_id, out.display());
+            println!("Register this public key for {}: {}", key_id, signer.public_key());
+            Ok(())
+        }
+    }
+}
+
+async fn run_local(dir: &Path, config: Option<&Path>, events: &[String]) -> ModuleResult<()> {
+    let config: Value = match config {
+        Some(path) => serde_json::from_str(&std::fs::read_to_string(path)?)?,
+        None => Value::Object(Default::default()),
+    };
+
+    let module = cli::load_local(dir).await?;
+    let mut harness = ModuleTestHarness::new(module)?;
+    harness.start(config).await?;
+    println!("Module {} is running; Ctrl-C to stop", harness.module().metadata().id);
+
+    for event in events {
+        let (event_type, data) = event.split_once('=')
+            .ok_or_else(|| ModuleError::ValidationFailed(format!("Expected TYPE=JSON, got {}", event)))?;
+        harness.publish_platform_event(event_type, serde_json::from_str(data)?).await?;
+        println!("Sent {}", event_type);
+    }
+
+    let mut interval = tokio::time::interval(std::time::Duration::from_secs(5));
+    let mut printed = 0;
+    loop {
+        tokio::select! {
+            _ = tokio::signal::ctrl_c() => break,
+            _ = interval.tick() => {
+                harness.deliver_events().await?;
+                let emitted = harness.emitted_events().await?;
+                for event in &emitted[printed..] {
+                    println!("Emitted {}: {}", event.event_type, event.data);
+                }
+                printed = emitted.len();
+                let health = harness.health().await?;
+                if !health.is_healthy {
+                    println!("warning: module reports unhealthy ({} errors)", health.error_count);
+                }
+            }
+        }
+    }
+
+    harness.stop().await?;
+    let usage = harness.usage(0.0).await?;
+    println!("Stopped; the module made {} host API calls", usage.api_calls);
+    Ok(())
+}
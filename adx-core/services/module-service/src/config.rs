@@ -1,6 +1,13 @@
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 
+use crate::events::EventBusConfig;
+use crate::metering::MeteringConfig;
+use crate::assets::AssetConfig;
+use crate::search::MarketplaceSearchConfig;
+use crate::hooks::LifecycleHookConfig;
+use crate::jobs::JobConfig;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModuleServiceConfig {
     pub server: ServerConfig,
@@ -10,6 +17,18 @@ pub struct ModuleServiceConfig {
     pub sandbox: SandboxConfig,
     pub security: SecurityConfig,
     pub monitoring: MonitoringConfig,
+    #[serde(default)]
+    pub events: EventBusConfig,
+    #[serde(default)]
+    pub metering: MeteringConfig,
+    #[serde(default)]
+    pub assets: AssetConfig,
+    #[serde(default)]
+    pub search: MarketplaceSearchConfig,
+    #[serde(default)]
+    pub hooks: LifecycleHookConfig,
+    #[serde(default)]
+    pub jobs: JobConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -75,6 +94,19 @@ pub struct SecurityConfig {
     pub min_security_score: u8,
     pub allowed_permissions: Vec<String>,
     pub blocked_permissions: Vec<String>,
+    /// Refuse to install packages without a developer signature and
+    /// marketplace countersignature
+    pub require_signed_packages: bool,
+    /// Marketplace countersigning keys by key id, as base64 Ed25519 public keys
+    pub marketplace_signing_keys: HashMap<String, String>,
+    pub revocation_sync_interval_seconds: u64,
+    /// OSV API that package dependencies are looked up in
+    #[serde(default = "default_vulnerability_db_url")]
+    pub vulnerability_db_url: String,
+}
+
+fn default_vulnerability_db_url() -> String {
+    "https://api.osv.dev".to_string()
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -135,6 +167,10 @@ impl Default for ModuleServiceConfig {
                 min_security_score: 70,
                 allowed_permissions: vec![],
                 blocked_permissions: vec![],
+                require_signed_packages: true,
+                marketplace_signing_keys: HashMap::new(),
+                revocation_sync_interval_seconds: 900,
+                vulnerability_db_url: default_vulnerability_db_url(),
             },
             monitoring: MonitoringConfig {
                 enable_metrics: true,
@@ -143,6 +179,12 @@ impl Default for ModuleServiceConfig {
                 resource_check_interval_seconds: 10,
                 log_level: "info".to_string(),
             },
+            events: EventBusConfig::default(),
+            metering: MeteringConfig::default(),
+            assets: AssetConfig::default(),
+            search: MarketplaceSearchConfig::default(),
+            hooks: LifecycleHookConfig::default(),
+            jobs: JobConfig::default(),
         }
     }
 }
\ No newline at end of file
@@ -32,6 +32,9 @@ pub enum ModuleError {
     #[error("Module security scan failed: {0}")]
     SecurityScanFailed(String),
     
+    #[error("Module signature verification failed: {0}")]
+    SignatureVerificationFailed(String),
+    
     #[error("Module sandbox violation: {0}")]
     SandboxViolation(String),
     
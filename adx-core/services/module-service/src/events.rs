@@ -0,0 +1,371 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use chrono::{DateTime, Utc};
+use jsonschema::JSONSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use tokio::sync::RwLock;
+use tracing::warn;
+use uuid::Uuid;
+
+use crate::{ModuleResult, ModuleError, ModuleRepository};
+
+/// An event a module declares it publishes, as `{module_id}.{name}`, with
+/// the JSON schema its payloads must match
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventDeclaration {
+    pub name: String,
+    #[serde(default)]
+    pub description: Option<String>,
+    pub schema: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BusEvent {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub event_type: String,
+    /// Publishing module; `None` for platform events
+    pub source_module: Option<String>,
+    pub data: Value,
+    pub published_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DeliveryStatus {
+    Pending,
+    /// Out of attempts; kept until an operator retries it
+    DeadLettered,
+}
+
+impl DeliveryStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeliveryStatus::Pending => "pending",
+            DeliveryStatus::DeadLettered => "dead_lettered",
+        }
+    }
+
+    pub fn parse(status: &str) -> ModuleResult<Self> {
+        match status {
+            "pending" => Ok(DeliveryStatus::Pending),
+            "dead_lettered" => Ok(DeliveryStatus::DeadLettered),
+            other => Err(ModuleError::SerializationError(format!("Unknown delivery status {}", other))),
+        }
+    }
+}
+
+/// An event on its way to one subscribing instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EventDelivery {
+    pub id: Uuid,
+    pub instance_id: Uuid,
+    pub event: BusEvent,
+    pub status: DeliveryStatus,
+    pub attempts: u32,
+    pub next_attempt_at: DateTime<Utc>,
+    pub last_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishPlatformEventRequest {
+    pub tenant_id: String,
+    pub event_type: String,
+    pub data: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct EventBusConfig {
+    /// Events each module may publish per tenant per minute
+    pub publish_limit_per_minute: u32,
+    pub max_payload_bytes: usize,
+    pub max_delivery_attempts: u32,
+    /// Delay before the first retry, doubled on each later one
+    pub retry_base_seconds: u64,
+    /// Platform events modules may subscribe to
+    pub platform_events: Vec<String>,
+}
+
+impl Default for EventBusConfig {
+    fn default() -> Self {
+        Self {
+            publish_limit_per_minute: 600,
+            max_payload_bytes: 64 * 1024,
+            max_delivery_attempts: 8,
+            retry_base_seconds: 5,
+            platform_events: vec![
+                "platform.tenant.switched".to_string(),
+                "platform.user.logged_in".to_string(),
+                "platform.user.logged_out".to_string(),
+                "platform.module.installed".to_string(),
+                "platform.module.uninstalled".to_string(),
+            ],
+        }
+    }
+}
+
+/// Compiled payload schemas by event type
+pub type EventSchemas = HashMap<String, Arc<JSONSchema>>;
+
+/// Namespace of the events the platform publishes
+const PLATFORM_NAMESPACE: &str = "platform";
+
+/// Longest retry delay, however many attempts have failed
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(3600);
+
+struct Subscription {
+    instance_id: Uuid,
+    tenant_id: String,
+    pattern: String,
+}
+
+struct RateWindow {
+    started: Instant,
+    published: u32,
+}
+
+/// Pub/sub between the module instances of a tenant. A module publishes the
+/// events its manifest declares, in its own namespace, and subscribes to any
+/// its capability grant covers, including selected platform events.
+/// Deliveries are stored and retried until the subscriber handles them, so
+/// each event reaches each subscriber at least once.
+pub struct EventBus {
+    config: EventBusConfig,
+    repository: Arc<dyn ModuleRepository>,
+    /// Schemas of the events each instance may publish
+    schemas: RwLock<HashMap<Uuid, EventSchemas>>,
+    subscriptions: RwLock<Vec<Subscription>>,
+    rate_windows: RwLock<HashMap<(String, String), RateWindow>>,
+}
+
+impl EventBus {
+    pub fn new(config: EventBusConfig, repository: Arc<dyn ModuleRepository>) -> Self {
+        Self {
+            config,
+            repository,
+            schemas: RwLock::new(HashMap::new()),
+            subscriptions: RwLock::new(Vec::new()),
+            rate_windows: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Compile the schemas of the events a module declares, before it is
+    /// installed
+    pub fn compile_schemas(module_id: &str, declarations: &[EventDeclaration]) -> ModuleResult<EventSchemas> {
+        if module_id == PLATFORM_NAMESPACE && !declarations.is_empty() {
+            return Err(ModuleError::ValidationFailed(
+                "Modules may not publish in the platform namespace".to_string()
+            ));
+        }
+
+        let mut schemas = EventSchemas::new();
+        for declaration in declarations {
+            let event_type = format!("{}.{}", module_id, declaration.name);
+            if declaration.name.is_empty() || declaration.name.contains('*') {
+                return Err(ModuleError::ValidationFailed(format!("Invalid event name {}", event_type)));
+            }
+
+            let schema = JSONSchema::compile(&declaration.schema)
+                .map_err(|e| ModuleError::ValidationFailed(format!("Schema of event {} is invalid: {}", event_type, e)))?;
+            schemas.insert(event_type, Arc::new(schema));
+        }
+        Ok(schemas)
+    }
+
+    /// Let an instance publish the events of its module version
+    pub async fn register_instance(&self, instance_id: Uuid, schemas: EventSchemas) {
+        self.schemas.write().await.insert(instance_id, schemas);
+    }
+
+    /// Drop an instance's schemas and subscriptions, when it is uninstalled
+    /// or before it is reloaded and registers again
+    pub async fn release_instance(&self, instance_id: Uuid) {
+        self.schemas.write().await.remove(&instance_id);
+        self.subscriptions.write().await
+            .retain(|subscription| subscription.instance_id != instance_id);
+    }
+
+    /// Deliver events matching `pattern` (exact, or ending in `*`) published
+    /// in the instance's tenant to the instance
+    pub async fn subscribe(&self, instance_id: Uuid, tenant_id: &str, pattern: &str) -> ModuleResult<()> {
+        if pattern.is_empty() || pattern.strip_suffix('*').unwrap_or(pattern).contains('*') {
+            return Err(ModuleError::ValidationFailed(format!("Invalid event pattern {}", pattern)));
+        }
+
+        let mut subscriptions = self.subscriptions.write().await;
+        let subscribed = subscriptions.iter()
+            .any(|subscription| subscription.instance_id == instance_id && subscription.pattern == pattern);
+        if !subscribed {
+            subscriptions.push(Subscription {
+                instance_id,
+                tenant_id: tenant_id.to_string(),
+                pattern: pattern.to_string(),
+            });
+        }
+        Ok(())
+    }
+
+    /// Publish `{module_id}.{name}` from a module instance
+    pub async fn publish(
+        &self,
+        instance_id: Uuid,
+        module_id: &str,
+        tenant_id: &str,
+        name: &str,
+        data: Value,
+    ) -> ModuleResult<BusEvent> {
+        let event_type = format!("{}.{}", module_id, name);
+        {
+            let schemas = self.schemas.read().await;
+            let schema = schemas.get(&instance_id)
+                .and_then(|schemas| schemas.get(&event_type))
+                .ok_or_else(|| ModuleError::ValidationFailed(
+                format!("Module {} does not declare event {}", module_id, event_type)
+            ))?;
+            if let Err(errors) = schema.validate(&data) {
+                let errors: Vec<String> = errors.map(|error| error.to_string()).collect();
+                return Err(ModuleError::ValidationFailed(
+                    format!("Event {} does not match its schema: {}", event_type, errors.join("; "))
+                ));
+            };
+        }
+        self.check_size(&event_type, &data)?;
+        self.check_rate(tenant_id, module_id).await?;
+
+        self.fan_out(BusEvent {
+            id: Uuid::new_v4(),
+            tenant_id: tenant_id.to_string(),
+            event_type,
+            source_module: Some(module_id.to_string()),
+            data,
+            published_at: Utc::now(),
+        }).await
+    }
+
+    /// Publish one of the platform events modules may subscribe to
+    pub async fn publish_platform_event(&self, tenant_id: &str, event_type: &str, data: Value) -> ModuleResult<BusEvent> {
+        if !self.config.platform_events.iter().any(|allowed| allowed == event_type) {
+            return Err(ModuleError::ValidationFailed(
+                format!("{} is not a platform event modules may receive", event_type)
+            ));
+        }
+        self.check_size(event_type, &data)?;
+
+        self.fan_out(BusEvent {
+            id: Uuid::new_v4(),
+            tenant_id: tenant_id.to_string(),
+            event_type: event_type.to_string(),
+            source_module: None,
+            data,
+            published_at: Utc::now(),
+        }).await
+    }
+
+    pub async fn due_deliveries(&self, limit: u32) -> ModuleResult<Vec<EventDelivery>> {
+        self.repository.due_event_deliveries(limit).await
+    }
+
+    pub async fn delivered(&self, delivery_id: Uuid) -> ModuleResult<()> {
+        self.repository.mark_event_delivered(delivery_id).await
+    }
+
+    /// Schedule a failed delivery's retry with exponential backoff, or
+    /// dead-letter it once it is out of attempts
+    pub async fn delivery_failed(&self, delivery: &EventDelivery, error: &str) -> ModuleResult<()> {
+        let attempts = delivery.attempts + 1;
+        let next_attempt_at = if attempts >= self.config.max_delivery_attempts {
+            warn!("Dead-lettered event {} for instance {} after {} attempts: {}",
+                  delivery.event.event_type, delivery.instance_id, attempts, error);
+            None
+        } else {
+            let delay = Duration::from_secs(self.config.retry_base_seconds)
+                .saturating_mul(2u32.saturating_pow(attempts - 1))
+                .min(MAX_RETRY_DELAY);
+            Some(Utc::now() + chrono::Duration::from_std(delay).unwrap_or_else(|_| chrono::Duration::hours(1)))
+        };
+
+        self.repository.mark_event_delivery_failed(delivery.id, attempts, error, next_attempt_at).await
+    }
+
+    pub async fn dead_letters(&self, tenant_id: &str) -> ModuleResult<Vec<EventDelivery>> {
+        self.repository.list_dead_lettered_deliveries(tenant_id).await
+    }
+
+    /// Put a dead-lettered delivery back in the queue with fresh attempts
+    pub async fn retry_dead_letter(&self, tenant_id: &str, delivery_id: Uuid) -> ModuleResult<()> {
+        if self.repository.requeue_event_delivery(tenant_id, delivery_id).await? {
+            return Ok(());
+        }
+        Err(ModuleError::NotFound(format!("Dead-lettered delivery {}", delivery_id)))
+    }
+
+    async fn fan_out(&self, event: BusEvent) -> ModuleResult<BusEvent> {
+        let mut subscribers: Vec<Uuid> = self.subscriptions.read().await
+            .iter()
+            .filter(|subscription| {
+                subscription.tenant_id == event.tenant_id
+                    && event_type_matches(&subscription.pattern, &event.event_type)
+            })
+            .map(|subscription| subscription.instance_id)
+            .collect();
+        subscribers.sort();
+        subscribers.dedup();
+
+        let deliveries: Vec<EventDelivery> = subscribers.into_iter()
+            .map(|instance_id| EventDelivery {
+                id: Uuid::new_v4(),
+                instance_id,
+                event: event.clone(),
+                status: DeliveryStatus::Pending,
+                attempts: 0,
+                next_attempt_at: event.published_at,
+                last_error: None,
+            })
+            .collect();
+        if !deliveries.is_empty() {
+            self.repository.save_event_deliveries(&deliveries).await?;
+        }
+
+        Ok(event)
+    }
+
+    fn check_size(&self, event_type: &str, data: &Value) -> ModuleResult<()> {
+        let size = serde_json::to_vec(data)?.len();
+        if size > self.config.max_payload_bytes {
+            return Err(ModuleError::ValidationFailed(format!(
+                "Event {} is {} bytes, more than {}", event_type, size, self.config.max_payload_bytes
+            )));
+        }
+        Ok(())
+    }
+
+    async fn check_rate(&self, tenant_id: &str, module_id: &str) -> ModuleResult<()> {
+        let mut windows = self.rate_windows.write().await;
+        let window = windows.entry((tenant_id.to_string(), module_id.to_string()))
+            .or_insert_with(|| RateWindow { started: Instant::now(), published: 0 });
+
+        if window.started.elapsed() >= Duration::from_secs(60) {
+            *window = RateWindow { started: Instant::now(), published: 0 };
+        }
+        if window.published >= self.config.publish_limit_per_minute {
+            return Err(ModuleError::ResourceLimitExceeded(format!(
+                "Module {} may publish {} events a minute", module_id, self.config.publish_limit_per_minute
+            )));
+        }
+
+        window.published += 1;
+        Ok(())
+    }
+}
+
+/// Whether an event type matches a subscription pattern: exactly, or by
+/// prefix when the pattern ends in `*`
+pub fn event_type_matches(pattern: &str, event_type: &str) -> bool {
+    match pattern.strip_suffix('*') {
+        Some(prefix) => event_type.starts_with(prefix),
+        None => pattern == event_type,
+    }
+}
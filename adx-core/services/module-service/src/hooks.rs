@@ -0,0 +1,196 @@
+use std::sync::Arc;
+use std::time::Duration;
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{ModuleResult, ModuleError, ModuleManifest, ModuleSandbox, LifecycleHook};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LifecycleHookConfig {
+    /// Timeout of hooks that do not set their own
+    pub default_timeout_seconds: u64,
+    /// Longest timeout a hook may set
+    pub max_timeout_seconds: u64,
+    /// Bytes of a failed hook's stderr kept in the error
+    pub max_error_output_bytes: usize,
+}
+
+impl Default for LifecycleHookConfig {
+    fn default() -> Self {
+        Self {
+            default_timeout_seconds: 300,
+            max_timeout_seconds: 1800,
+            max_error_output_bytes: 4096,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HookPhase {
+    Install,
+    Upgrade,
+    Uninstall,
+}
+
+impl HookPhase {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            HookPhase::Install => "install",
+            HookPhase::Upgrade => "upgrade",
+            HookPhase::Uninstall => "uninstall",
+        }
+    }
+}
+
+/// What a hook is told about the operation it runs in. Hooks get the phase
+/// as their first argument and this, as JSON, as their second.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookContext {
+    pub phase: HookPhase,
+    pub instance_id: Uuid,
+    pub tenant_id: String,
+    pub module_id: String,
+    /// The installed version, on upgrade and uninstall
+    pub from_version: Option<Version>,
+    /// The version being installed or upgraded to
+    pub to_version: Option<Version>,
+    /// Whether the tenant's data goes with the module, on uninstall
+    pub cleanup_data: bool,
+    /// Set when the hook's compensation runs
+    pub compensating: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HookRun {
+    pub phase: HookPhase,
+    pub compensating: bool,
+    pub duration_ms: u64,
+    pub stdout: String,
+}
+
+/// Runs the lifecycle hooks modules declare in their manifest, each in a
+/// sandbox of its own with a timeout
+pub struct LifecycleHookRunner {
+    config: LifecycleHookConfig,
+    sandbox: Arc<dyn ModuleSandbox>,
+}
+
+impl LifecycleHookRunner {
+    pub fn new(config: LifecycleHookConfig, sandbox: Arc<dyn ModuleSandbox>) -> Self {
+        Self { config, sandbox }
+    }
+
+    pub fn hook<'a>(manifest: &'a ModuleManifest, phase: HookPhase) -> Option<&'a LifecycleHook> {
+        let hooks = &manifest.lifecycle_hooks;
+        match phase {
+            HookPhase::Install => hooks.install.as_ref(),
+            HookPhase::Upgrade => hooks.upgrade.as_ref(),
+            HookPhase::Uninstall => hooks.uninstall.as_ref(),
+        }
+    }
+
+    /// Run the manifest's hook for the context's phase, if it declares one.
+    /// A hook that fails or times out may have got part way, so its
+    /// compensation is run before the error is returned.
+    pub async fn run(&self, manifest: &ModuleManifest, context: &HookContext) -> ModuleResult<Option<HookRun>> {
+        let Some(hook) = Self::hook(manifest, context.phase) else {
+            return Ok(None);
+        };
+
+        match self.execute(&hook.run, hook, context).await {
+            Ok(run) => {
+                info!(
+                    "{} hook of {} ran for instance {} in {}ms",
+                    context.phase.as_str(), context.module_id, context.instance_id, run.duration_ms
+                );
+                Ok(Some(run))
+            }
+            Err(e) => {
+                warn!("{} hook of {} failed for instance {}: {}", context.phase.as_str(), context.module_id, context.instance_id, e);
+                self.compensate(manifest, context).await;
+                Err(e)
+            }
+        }
+    }
+
+    /// Undo a hook after it or a later step of its operation failed.
+    /// Compensation is best effort: its errors are logged so they do not
+    /// hide the failure that caused it.
+    pub async fn compensate(&self, manifest: &ModuleManifest, context: &HookContext) {
+        let Some(hook) = Self::hook(manifest, context.phase) else {
+            return;
+        };
+        let Some(script) = hook.compensate.as_deref() else {
+            warn!(
+                "{} hook of {} has no compensation; instance {} may be left part way",
+                context.phase.as_str(), context.module_id, context.instance_id
+            );
+            return;
+        };
+
+        let context = HookContext { compensating: true, ..context.clone() };
+        match self.execute(script, hook, &context).await {
+            Ok(_) => info!(
+                "Compensated {} hook of {} for instance {}",
+                context.phase.as_str(), context.module_id, context.instance_id
+            ),
+            Err(e) => warn!(
+                "Compensating {} hook of {} failed for instance {}: {}",
+                context.phase.as_str(), context.module_id, context.instance_id, e
+            ),
+        }
+    }
+
+    async fn execute(&self, script: &str, hook: &LifecycleHook, context: &HookContext) -> ModuleResult<HookRun> {
+        let timeout = hook.timeout_seconds
+            .unwrap_or(self.config.default_timeout_seconds)
+            .min(self.config.max_timeout_seconds);
+        let args = vec![context.phase.as_str().to_string(), serde_json::to_string(context)?];
+
+        let handle = self.sandbox.create_sandbox(context.instance_id).await?;
+        let result = tokio::time::timeout(
+            Duration::from_secs(timeout),
+            self.sandbox.execute_in_sandbox(&handle, script, args),
+        ).await;
+        if let Err(e) = self.sandbox.destroy_sandbox(handle).await {
+            warn!("Failed to destroy hook sandbox of instance {}: {}", context.instance_id, e);
+        }
+
+        let result = result.map_err(|_| ModuleError::ResourceLimitExceeded(format!(
+            "{} hook of {} timed out after {}s", context.phase.as_str(), context.module_id, timeout
+        )))??;
+        if result.exit_code != 0 {
+            return Err(ModuleError::RuntimeError(format!(
+                "{} hook of {} exited with {}: {}",
+                context.phase.as_str(),
+                context.module_id,
+                result.exit_code,
+                tail(&result.stderr, self.config.max_error_output_bytes)
+            )));
+        }
+
+        Ok(HookRun {
+            phase: context.phase,
+            compensating: context.compensating,
+            duration_ms: result.execution_time_ms,
+            stdout: result.stdout,
+        })
+    }
+}
+
+/// The end of a hook's output, where the error usually is
+fn tail(output: &str, max_bytes: usize) -> &str {
+    let output = output.trim_end();
+    if output.len() <= max_bytes {
+        return output;
+    }
+    let mut start = output.len() - max_bytes;
+    while !output.is_char_boundary(start) {
+        start += 1;
+    }
+    &output[start..]
+}
@@ -0,0 +1,577 @@
+use std::future::Future;
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{ModuleResult, ModuleError, ModuleRepository, ModuleStatus};
+
+/// Scope a module needs to schedule background jobs
+pub const JOBS_SCOPE: &str = "jobs:schedule";
+
+/// Workflow-service workflow that starts job runs
+const JOB_WORKFLOW_TYPE: &str = "module_job_workflow";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct JobConfig {
+    /// Workflow-service, whose scheduler starts the runs
+    pub workflow_service_url: String,
+    pub max_jobs_per_instance: u32,
+    /// Runs of one instance's jobs at a time; runs over it are skipped
+    pub max_concurrent_runs: u32,
+    pub default_timeout_seconds: u64,
+    pub max_timeout_seconds: u64,
+    /// Runs kept per job
+    pub run_history: u32,
+}
+
+impl Default for JobConfig {
+    fn default() -> Self {
+        Self {
+            workflow_service_url: "http://localhost:8084".to_string(),
+            max_jobs_per_instance: 20,
+            max_concurrent_runs: 2,
+            default_timeout_seconds: 300,
+            max_timeout_seconds: 3600,
+            run_history: 50,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum JobSchedule {
+    /// A cron expression, in `timezone` or UTC
+    Recurring {
+        cron: String,
+        #[serde(default)]
+        timezone: Option<String>,
+    },
+    /// One run at a time, to the minute
+    Once { at: DateTime<Utc> },
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobState {
+    Active,
+    /// Disabled by a tenant admin; its runs are not started
+    Disabled,
+    /// A one-off job that has run
+    Completed,
+}
+
+impl JobState {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobState::Active => "active",
+            JobState::Disabled => "disabled",
+            JobState::Completed => "completed",
+        }
+    }
+
+    pub fn parse(state: &str) -> ModuleResult<Self> {
+        match state {
+            "active" => Ok(JobState::Active),
+            "disabled" => Ok(JobState::Disabled),
+            "completed" => Ok(JobState::Completed),
+            other => Err(ModuleError::SerializationError(format!("Unknown job state {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum JobRunStatus {
+    Running,
+    Succeeded,
+    Failed,
+    TimedOut,
+    /// Not run: the job was disabled, the module inactive or at its
+    /// concurrency limit
+    Skipped,
+}
+
+impl JobRunStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JobRunStatus::Running => "running",
+            JobRunStatus::Succeeded => "succeeded",
+            JobRunStatus::Failed => "failed",
+            JobRunStatus::TimedOut => "timed_out",
+            JobRunStatus::Skipped => "skipped",
+        }
+    }
+
+    pub fn parse(status: &str) -> ModuleResult<Self> {
+        match status {
+            "running" => Ok(JobRunStatus::Running),
+            "succeeded" => Ok(JobRunStatus::Succeeded),
+            "failed" => Ok(JobRunStatus::Failed),
+            "timed_out" => Ok(JobRunStatus::TimedOut),
+            "skipped" => Ok(JobRunStatus::Skipped),
+            other => Err(ModuleError::SerializationError(format!("Unknown job run status {}", other))),
+        }
+    }
+}
+
+/// A background job a module instance registered
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleJob {
+    pub id: Uuid,
+    pub instance_id: Uuid,
+    pub tenant_id: String,
+    pub module_id: String,
+    /// Unique within the instance
+    pub name: String,
+    pub schedule: JobSchedule,
+    /// Handed to the module's handler on every run
+    pub payload: Value,
+    pub timeout_seconds: u64,
+    /// Workflow-service schedule that starts the runs
+    pub schedule_id: Option<String>,
+    pub state: JobState,
+    pub disabled_reason: Option<String>,
+    pub disabled_by: Option<String>,
+    pub consecutive_failures: u32,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub last_run_status: Option<JobRunStatus>,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JobRun {
+    pub id: Uuid,
+    pub job_id: Uuid,
+    pub instance_id: Uuid,
+    pub tenant_id: String,
+    pub status: JobRunStatus,
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub finished_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterJobRequest {
+    pub name: String,
+    pub schedule: JobSchedule,
+    #[serde(default)]
+    pub payload: Value,
+    /// Defaults to the service's job timeout
+    #[serde(default)]
+    pub timeout_seconds: Option<u64>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DisableJobRequest {
+    pub disabled_by: String,
+    #[serde(default)]
+    pub reason: Option<String>,
+}
+
+/// Background jobs of module instances. Runs are started by workflow-service
+/// schedules, which call back into module-service; a run is handed to the
+/// module as a `JobTriggered` event and recorded with its outcome.
+pub struct ModuleJobScheduler {
+    config: JobConfig,
+    repository: Arc<dyn ModuleRepository>,
+    /// `None` when runs are only started by hand, as in the test harness
+    schedules: Option<WorkflowScheduleClient>,
+}
+
+impl ModuleJobScheduler {
+    pub fn new(config: JobConfig, repository: Arc<dyn ModuleRepository>) -> Self {
+        let schedules = Some(WorkflowScheduleClient::new(&config.workflow_service_url));
+        Self { config, repository, schedules }
+    }
+
+    /// A scheduler that keeps jobs without scheduling them
+    pub fn local(config: JobConfig, repository: Arc<dyn ModuleRepository>) -> Self {
+        Self { config, repository, schedules: None }
+    }
+
+    /// Register a job for an instance. Registering the same job again, as
+    /// a module does each time it initializes, keeps it as it is; a changed
+    /// schedule replaces the old one. A tenant admin's disabling stands.
+    pub async fn register(
+        &self,
+        instance_id: Uuid,
+        module_id: &str,
+        tenant_id: &str,
+        request: RegisterJobRequest,
+    ) -> ModuleResult<ModuleJob> {
+        check_job_name(&request.name)?;
+        let timeout_seconds = request.timeout_seconds.unwrap_or(self.config.default_timeout_seconds);
+        if timeout_seconds == 0 || timeout_seconds > self.config.max_timeout_seconds {
+            return Err(ModuleError::ValidationFailed(format!(
+                "Job timeout must be between 1 and {} seconds", self.config.max_timeout_seconds
+            )));
+        }
+        if let JobSchedule::Once { at } = &request.schedule {
+            if *at <= Utc::now() {
+                return Err(ModuleError::ValidationFailed(format!("Job {} is scheduled in the past", request.name)));
+            }
+        }
+
+        let now = Utc::now();
+        let existing = self.repository.find_module_job(instance_id, &request.name).await?;
+        let mut job = match existing {
+            Some(job) if job.schedule == request.schedule
+                && job.payload == request.payload
+                && job.timeout_seconds == timeout_seconds =>
+            {
+                return Ok(job);
+            }
+            Some(mut job) => {
+                self.delete_schedule(&job).await;
+                job.schedule = request.schedule;
+                job.payload = request.payload;
+                job.timeout_seconds = timeout_seconds;
+                job.schedule_id = None;
+                if job.state == JobState::Completed {
+                    job.state = JobState::Active;
+                }
+                job.updated_at = now;
+                job
+            }
+            None => {
+                let registered = self.repository.list_instance_jobs(instance_id).await?.len() as u32;
+                if registered >= self.config.max_jobs_per_instance {
+                    return Err(ModuleError::ResourceLimitExceeded(format!(
+                        "Module {} already has {} of {} allowed jobs", module_id, registered, self.config.max_jobs_per_instance
+                    )));
+                }
+                ModuleJob {
+                    id: Uuid::new_v4(),
+                    instance_id,
+                    tenant_id: tenant_id.to_string(),
+                    module_id: module_id.to_string(),
+                    name: request.name,
+                    schedule: request.schedule,
+                    payload: request.payload,
+                    timeout_seconds,
+                    schedule_id: None,
+                    state: JobState::Active,
+                    disabled_reason: None,
+                    disabled_by: None,
+                    consecutive_failures: 0,
+                    last_run_at: None,
+                    last_run_status: None,
+                    last_error: None,
+                    created_at: now,
+                    updated_at: now,
+                }
+            }
+        };
+
+        if let Some(schedules) = &self.schedules {
+            let schedule_id = schedules.create(&job).await?;
+            if job.state == JobState::Disabled {
+                let note = job.disabled_reason.clone().unwrap_or_else(|| "Disabled by a tenant admin".to_string());
+                schedules.pause(tenant_id, &schedule_id, &note).await?;
+            }
+            job.schedule_id = Some(schedule_id);
+        }
+        if let Err(e) = self.repository.save_module_job(&job).await {
+            // Don't leave a schedule running that no job answers
+            self.delete_schedule(&job).await;
+            return Err(e);
+        }
+
+        info!("Registered job {} of {} for instance {}", job.name, module_id, instance_id);
+        Ok(job)
+    }
+
+    /// Remove an instance's job and its runs
+    pub async fn cancel(&self, instance_id: Uuid, name: &str) -> ModuleResult<()> {
+        let job = self.repository.find_module_job(instance_id, name).await?
+            .ok_or_else(|| ModuleError::NotFound(format!("Job {} of instance {}", name, instance_id)))?;
+        self.delete_schedule(&job).await;
+        self.repository.delete_module_job(job.id).await
+    }
+
+    /// Remove the jobs of an uninstalled instance
+    pub async fn release_instance(&self, instance_id: Uuid) -> ModuleResult<()> {
+        for job in self.repository.list_instance_jobs(instance_id).await? {
+            self.delete_schedule(&job).await;
+            self.repository.delete_module_job(job.id).await?;
+        }
+        Ok(())
+    }
+
+    pub async fn get(&self, tenant_id: &str, job_id: Uuid) -> ModuleResult<ModuleJob> {
+        self.repository.get_module_job(job_id).await?
+            .filter(|job| job.tenant_id == tenant_id)
+            .ok_or_else(|| ModuleError::NotFound(format!("Job {}", job_id)))
+    }
+
+    pub async fn get_by_name(&self, instance_id: Uuid, name: &str) -> ModuleResult<ModuleJob> {
+        self.repository.find_module_job(instance_id, name).await?
+            .ok_or_else(|| ModuleError::NotFound(format!("Job {} of instance {}", name, instance_id)))
+    }
+
+    pub async fn list_tenant_jobs(&self, tenant_id: &str) -> ModuleResult<Vec<ModuleJob>> {
+        self.repository.list_tenant_jobs(tenant_id).await
+    }
+
+    pub async fn list_instance_jobs(&self, instance_id: Uuid) -> ModuleResult<Vec<ModuleJob>> {
+        self.repository.list_instance_jobs(instance_id).await
+    }
+
+    /// A job's runs, newest first
+    pub async fn list_runs(&self, tenant_id: &str, job_id: Uuid) -> ModuleResult<Vec<JobRun>> {
+        let job = self.get(tenant_id, job_id).await?;
+        self.repository.list_job_runs(job.id, self.config.run_history).await
+    }
+
+    /// Stop starting a job's runs, for a tenant admin silencing a noisy job
+    pub async fn disable(&self, tenant_id: &str, job_id: Uuid, request: DisableJobRequest) -> ModuleResult<ModuleJob> {
+        if request.disabled_by.trim().is_empty() {
+            return Err(ModuleError::ValidationFailed("disabled_by is required".to_string()));
+        }
+        let mut job = self.get(tenant_id, job_id).await?;
+        if job.state != JobState::Active {
+            return Err(ModuleError::ValidationFailed(format!("Job {} is {}", job_id, job.state.as_str())));
+        }
+
+        if let (Some(schedules), Some(schedule_id)) = (&self.schedules, &job.schedule_id) {
+            let note = request.reason.clone().unwrap_or_else(|| format!("Disabled by {}", request.disabled_by));
+            schedules.pause(tenant_id, schedule_id, &note).await?;
+        }
+        job.state = JobState::Disabled;
+        job.disabled_reason = request.reason;
+        job.disabled_by = Some(request.disabled_by);
+        job.updated_at = Utc::now();
+        self.repository.save_module_job(&job).await?;
+
+        info!("Disabled job {} of {} for tenant {}", job.name, job.module_id, tenant_id);
+        Ok(job)
+    }
+
+    /// Start a disabled job's runs again, with its failure count reset
+    pub async fn enable(&self, tenant_id: &str, job_id: Uuid) -> ModuleResult<ModuleJob> {
+        let mut job = self.get(tenant_id, job_id).await?;
+        if job.state != JobState::Disabled {
+            return Err(ModuleError::ValidationFailed(format!("Job {} is not disabled", job_id)));
+        }
+
+        if let (Some(schedules), Some(schedule_id)) = (&self.schedules, &job.schedule_id) {
+            schedules.resume(tenant_id, schedule_id).await?;
+        }
+        job.state = JobState::Active;
+        job.disabled_reason = None;
+        job.disabled_by = None;
+        job.consecutive_failures = 0;
+        job.updated_at = Utc::now();
+        self.repository.save_module_job(&job).await?;
+
+        info!("Enabled job {} of {} for tenant {}", job.name, job.module_id, tenant_id);
+        Ok(job)
+    }
+
+    /// Run a job now: `dispatch` hands it to the module and is given the
+    /// job's timeout. The run is skipped if the job is not active, the
+    /// instance is not active or its jobs are at their concurrency limit.
+    pub async fn execute<F, Fut>(&self, tenant_id: &str, job_id: Uuid, dispatch: F) -> ModuleResult<JobRun>
+    where
+        F: FnOnce(ModuleJob, Uuid) -> Fut,
+        Fut: Future<Output = ModuleResult<()>>,
+    {
+        let mut job = self.get(tenant_id, job_id).await?;
+        let mut run = JobRun {
+            id: Uuid::new_v4(),
+            job_id: job.id,
+            instance_id: job.instance_id,
+            tenant_id: job.tenant_id.clone(),
+            status: JobRunStatus::Running,
+            error: None,
+            started_at: Utc::now(),
+            finished_at: None,
+        };
+
+        if let Some(reason) = self.skip_reason(&job).await? {
+            run.status = JobRunStatus::Skipped;
+            run.error = Some(reason);
+            run.finished_at = Some(Utc::now());
+            self.repository.save_job_run(&run).await?;
+            return Ok(run);
+        }
+        self.repository.save_job_run(&run).await?;
+
+        let timeout = Duration::from_secs(job.timeout_seconds);
+        let result = tokio::time::timeout(timeout, dispatch(job.clone(), run.id)).await;
+        let (status, error) = match result {
+            Ok(Ok(())) => (JobRunStatus::Succeeded, None),
+            Ok(Err(e)) => (JobRunStatus::Failed, Some(e.to_string())),
+            Err(_) => (JobRunStatus::TimedOut, Some(format!("Timed out after {}s", job.timeout_seconds))),
+        };
+        run.status = status;
+        run.error = error.clone();
+        run.finished_at = Some(Utc::now());
+        self.repository.save_job_run(&run).await?;
+
+        job.last_run_at = Some(run.started_at);
+        job.last_run_status = Some(status);
+        job.last_error = error;
+        if status == JobRunStatus::Succeeded {
+            job.consecutive_failures = 0;
+        } else {
+            job.consecutive_failures += 1;
+            warn!(
+                "Job {} of {} failed for tenant {} ({} in a row): {}",
+                job.name,
+                job.module_id,
+                job.tenant_id,
+                job.consecutive_failures,
+                job.last_error.as_deref().unwrap_or_default()
+            );
+        }
+        if matches!(job.schedule, JobSchedule::Once { .. }) {
+            self.delete_schedule(&job).await;
+            job.schedule_id = None;
+            job.state = JobState::Completed;
+        }
+        job.updated_at = Utc::now();
+        self.repository.save_module_job(&job).await?;
+        self.repository.prune_job_runs(job.id, self.config.run_history).await?;
+
+        Ok(run)
+    }
+
+    async fn skip_reason(&self, job: &ModuleJob) -> ModuleResult<Option<String>> {
+        if job.state != JobState::Active {
+            return Ok(Some(format!("Job is {}", job.state.as_str())));
+        }
+        let active = self.repository.get_instance(job.instance_id).await?
+            .is_some_and(|instance| matches!(instance.status, ModuleStatus::Active));
+        if !active {
+            return Ok(Some("Module is not active".to_string()));
+        }
+
+        // Runs left running past the longest timeout died with their replica
+        let since = Utc::now() - chrono::Duration::seconds(self.config.max_timeout_seconds as i64);
+        let running = self.repository.count_running_job_runs(job.instance_id, since).await?;
+        if running >= self.config.max_concurrent_runs {
+            return Ok(Some(format!("{} runs of the module's jobs are already running", running)));
+        }
+        Ok(None)
+    }
+
+    /// Schedules are removed best effort; a schedule left behind only
+    /// starts runs of a job that is gone, which fail as not found
+    async fn delete_schedule(&self, job: &ModuleJob) {
+        if let (Some(schedules), Some(schedule_id)) = (&self.schedules, &job.schedule_id) {
+            if let Err(e) = schedules.delete(&job.tenant_id, schedule_id).await {
+                warn!("Failed to delete schedule {} of job {}: {}", schedule_id, job.id, e);
+            }
+        }
+    }
+}
+
+fn check_job_name(name: &str) -> ModuleResult<()> {
+    let valid = !name.is_empty()
+        && name.len() <= 64
+        && name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_');
+    if !valid {
+        return Err(ModuleError::ValidationFailed(format!(
+            "Invalid job name {}; use up to 64 lowercase letters, digits, - and _", name
+        )));
+    }
+    Ok(())
+}
+
+/// Client of workflow-service's schedule API. Module jobs count against the
+/// tenant's schedule limits there.
+struct WorkflowScheduleClient {
+    base_url: String,
+    client: reqwest::Client,
+}
+
+impl WorkflowScheduleClient {
+    fn new(base_url: &str) -> Self {
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Create the schedule that starts a job's runs; returns its id
+    async fn create(&self, job: &ModuleJob) -> ModuleResult<String> {
+        let (cron_expression, timezone) = match &job.schedule {
+            JobSchedule::Recurring { cron, timezone } => (cron.clone(), timezone.clone()),
+            JobSchedule::Once { at } => (
+                format!("{} {} {} {} *", at.minute(), at.hour(), at.day(), at.month()),
+                Some("UTC".to_string()),
+            ),
+        };
+        let body = json!({
+            "name": format!("Module job {}/{}", job.module_id, job.name),
+            "description": format!("Background job {} of module instance {}", job.name, job.instance_id),
+            "workflow_type": JOB_WORKFLOW_TYPE,
+            "cron_expression": cron_expression,
+            "timezone": timezone,
+            "input": {
+                "tenant_id": job.tenant_id,
+                "job_id": job.id,
+                "module_id": job.module_id,
+                "job_name": job.name,
+            },
+            "start_paused": job.state == JobState::Disabled,
+        });
+
+        let response = self.client
+            .post(format!("{}/api/v1/workflow-schedules", self.base_url))
+            .header("X-Tenant-ID", &job.tenant_id)
+            .header("X-User-ID", format!("module:{}", job.module_id))
+            .json(&body)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            let message = response.text().await.unwrap_or_default();
+            return Err(ModuleError::NetworkError(format!(
+                "Workflow service refused the schedule of job {} ({}): {}", job.name, status, message
+            )));
+        }
+
+        let schedule: Value = response.json().await?;
+        schedule["schedule_id"].as_str()
+            .map(str::to_string)
+            .ok_or_else(|| ModuleError::NetworkError("Workflow service returned no schedule_id".to_string()))
+    }
+
+    async fn pause(&self, tenant_id: &str, schedule_id: &str, reason: &str) -> ModuleResult<()> {
+        let request = self.client
+            .post(format!("{}/api/v1/workflow-schedules/{}/pause", self.base_url, schedule_id))
+            .json(&json!({ "reason": reason }));
+        self.send(request, tenant_id, schedule_id).await
+    }
+
+    async fn resume(&self, tenant_id: &str, schedule_id: &str) -> ModuleResult<()> {
+        let request = self.client
+            .post(format!("{}/api/v1/workflow-schedules/{}/resume", self.base_url, schedule_id));
+        self.send(request, tenant_id, schedule_id).await
+    }
+
+    async fn delete(&self, tenant_id: &str, schedule_id: &str) -> ModuleResult<()> {
+        let request = self.client
+            .delete(format!("{}/api/v1/workflow-schedules/{}", self.base_url, schedule_id));
+        self.send(request, tenant_id, schedule_id).await
+    }
+
+    async fn send(&self, request: reqwest::RequestBuilder, tenant_id: &str, schedule_id: &str) -> ModuleResult<()> {
+        let response = request.header("X-Tenant-ID", tenant_id).send().await?;
+        if !response.status().is_success() {
+            return Err(ModuleError::NetworkError(format!(
+                "Workflow service returned {} for schedule {}", response.status(), schedule_id
+            )));
+        }
+        Ok(())
+    }
+}
@@ -12,11 +12,56 @@ pub mod sdk;
 pub mod registry;
 pub mod loader;
 pub mod runtime;
+pub mod permissions;
+pub mod signing;
+pub mod rollout;
+pub mod events;
+pub mod settings;
+pub mod metering;
+pub mod assets;
+pub mod search;
+pub mod review;
+pub mod hooks;
+pub mod jobs;
 
 pub use config::ModuleServiceConfig;
 pub use error::{ModuleError, ModuleResult};
 pub use models::*;
 pub use traits::*;
+pub use permissions::{
+    CapabilityManifest, CapabilityGrants, CapabilityGrant, CapabilityConsent, ConsentScreen,
+};
+pub use signing::{
+    PackageSignature, Countersignature, DeveloperKey, Revocation, RevocationKind,
+    PackageSigner, PackageVerifier, RegisterDeveloperKeyRequest, RevokeRequest,
+};
+pub use rollout::{
+    ModuleRollout, RolloutPolicy, RolloutStatus, RolloutController, StartRolloutRequest,
+};
+pub use events::{
+    EventBus, EventBusConfig, EventDeclaration, BusEvent, EventDelivery, DeliveryStatus,
+    PublishPlatformEventRequest,
+};
+pub use settings::{ModuleSettings, ModuleSettingsView, UpdateSettingsRequest};
+pub use metering::{
+    MeteringConfig, UsageMeter, BillingFeed, ModuleUsage, UsageTotals, TenantUsageReport, UsageRates,
+};
+pub use assets::{
+    AssetConfig, ModuleAssetStore, ModuleAsset, FrontendBundle, FrontendManifest, FrontendModule,
+};
+pub use search::{
+    MarketplaceSearchConfig, MarketplaceIndex, MarketplaceListing, MarketplaceSearchQuery,
+    MarketplaceSearchResult, MarketplaceSearchHit, MarketplaceFacets, TenantProfile, RecommendationRequest,
+    ListingSecurity,
+};
+pub use security::{ReviewStatus, ScanStage, SecurityReport, StageOutcome, StageReport};
+pub use review::{ReviewDecision, SecurityReviewQueue};
+pub use hooks::{LifecycleHookConfig, LifecycleHookRunner, HookPhase, HookContext, HookRun};
+pub use jobs::{
+    JobConfig, ModuleJobScheduler, ModuleJob, JobRun, JobSchedule, JobState, JobRunStatus,
+    RegisterJobRequest, DisableJobRequest, JOBS_SCOPE,
+};
+pub use sdk::harness::ModuleTestHarness;
 pub use manager::ModuleManager;
 pub use marketplace::ModuleMarketplace;
 pub use sandbox::ModuleSandbox;
\ No newline at end of file
@@ -1,8 +1,8 @@
 use std::sync::Arc;
 use axum::{
     extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
     routing::{get, post, put, delete},
     Router,
 };
@@ -16,7 +16,7 @@ use module_service::{
     ModuleServiceConfig, ModuleResult, ModuleError,
     runtime::ModuleServiceRuntime,
     InstallModuleRequest, UpdateModuleRequest, UninstallModuleRequest,
-    ModuleSearchQuery, ModulePurchase, ModuleReview,
+    MarketplaceSearchQuery, RecommendationRequest, ModulePurchase, ModuleReview,
 };
 
 #[derive(Clone)]
@@ -55,11 +55,18 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .route("/api/v1/modules/:instance_id/health", get(get_module_health))
         .route("/api/v1/modules/:instance_id/resources", get(get_module_resources))
         
+        // Module settings
+        .route("/api/v1/modules/:instance_id/settings", get(get_module_settings).put(update_module_settings))
+        .route("/api/v1/modules/:instance_id/settings/versions", get(list_module_settings_versions))
+        
         // Tenant module management
         .route("/api/v1/tenants/:tenant_id/modules", get(list_tenant_modules))
+        .route("/api/v1/tenants/:tenant_id/modules/:module_id/consent", get(get_consent_screen))
+        .route("/api/v1/tenants/:tenant_id/modules/:module_id/grant", get(get_capability_grant))
         
         // Marketplace endpoints
         .route("/api/v1/marketplace/search", post(search_marketplace))
+        .route("/api/v1/marketplace/recommendations", post(recommend_modules))
         .route("/api/v1/marketplace/modules/:module_id", get(get_marketplace_module))
         .route("/api/v1/marketplace/featured", get(get_featured_modules))
         .route("/api/v1/marketplace/trending", get(get_trending_modules))
@@ -69,6 +76,40 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         .route("/api/v1/marketplace/modules/:module_id/reviews", get(get_module_reviews))
         .route("/api/v1/marketplace/reviews", post(submit_module_review))
         
+        // Rollout endpoints
+        .route("/api/v1/rollouts", get(list_module_rollouts).post(start_rollout))
+        .route("/api/v1/rollouts/:rollout_id", get(get_rollout))
+        .route("/api/v1/rollouts/:rollout_id/pause", post(pause_rollout))
+        .route("/api/v1/rollouts/:rollout_id/resume", post(resume_rollout))
+        .route("/api/v1/rollouts/:rollout_id/rollback", post(rollback_rollout))
+        
+        // Package signing endpoints
+        .route("/api/v1/signing/developer-keys", post(register_developer_key))
+        .route("/api/v1/signing/developer-keys/:key_id", get(get_developer_key))
+        .route("/api/v1/signing/revocations", get(list_revocations).post(revoke))
+        
+        // Event bus endpoints
+        .route("/api/v1/events/platform", post(publish_platform_event))
+        .route("/api/v1/tenants/:tenant_id/events/dead-letters", get(list_dead_letters))
+        .route("/api/v1/tenants/:tenant_id/events/dead-letters/:delivery_id/retry", post(retry_dead_letter))
+        .route("/api/v1/tenants/:tenant_id/usage", get(get_tenant_usage))
+        
+        // Security review endpoints
+        .route("/api/v1/security/scans", post(scan_marketplace_module))
+        .route("/api/v1/security/reports/:scan_id", get(get_security_report))
+        .route("/api/v1/security/reviews", get(list_security_reviews))
+        .route("/api/v1/security/reviews/:scan_id", post(review_security_report))
+        
+        // Module job endpoints
+        .route("/api/v1/tenants/:tenant_id/jobs", get(list_module_jobs))
+        .route("/api/v1/tenants/:tenant_id/jobs/:job_id/runs", get(list_module_job_runs).post(run_module_job))
+        .route("/api/v1/tenants/:tenant_id/jobs/:job_id/disable", post(disable_module_job))
+        .route("/api/v1/tenants/:tenant_id/jobs/:job_id/enable", post(enable_module_job))
+        
+        // Frontend asset endpoints
+        .route("/api/v1/modules/assets/:module_id/:version/*path", get(get_module_asset))
+        .route("/api/v1/modules/frontend-manifest/:tenant_id", get(get_frontend_manifest))
+        
         // Workflow endpoints
         .route("/api/v1/workflows/install-module", post(install_module_workflow))
         .route("/api/v1/workflows/update-module", post(update_module_workflow))
@@ -199,6 +240,37 @@ async fn get_module_resources(
     }
 }
 
+async fn get_module_settings(
+    State(state): State<AppState>,
+    Path(instance_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<module_service::ModuleSettingsView>>, ApiError> {
+    match state.runtime.get_module_settings(instance_id).await {
+        Ok(settings) => Ok(Json(ApiResponse::success(settings))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+async fn update_module_settings(
+    State(state): State<AppState>,
+    Path(instance_id): Path<Uuid>,
+    Json(request): Json<module_service::UpdateSettingsRequest>,
+) -> Result<Json<ApiResponse<module_service::ModuleSettings>>, ApiError> {
+    match state.runtime.update_module_settings(instance_id, request).await {
+        Ok(settings) => Ok(Json(ApiResponse::success(settings))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+async fn list_module_settings_versions(
+    State(state): State<AppState>,
+    Path(instance_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<Vec<module_service::ModuleSettings>>>, ApiError> {
+    match state.runtime.list_module_settings_versions(instance_id).await {
+        Ok(versions) => Ok(Json(ApiResponse::success(versions))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
 async fn list_tenant_modules(
     State(state): State<AppState>,
     Path(tenant_id): Path<String>,
@@ -209,18 +281,54 @@ async fn list_tenant_modules(
     }
 }
 
+#[derive(Debug, Deserialize)]
+struct ConsentQuery {
+    version: Option<String>,
+}
+
+async fn get_consent_screen(
+    State(state): State<AppState>,
+    Path((tenant_id, module_id)): Path<(String, String)>,
+    Query(query): Query<ConsentQuery>,
+) -> Result<Json<ApiResponse<module_service::ConsentScreen>>, ApiError> {
+    match state.runtime.get_consent_screen(&tenant_id, &module_id, query.version).await {
+        Ok(screen) => Ok(Json(ApiResponse::success(screen))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+async fn get_capability_grant(
+    State(state): State<AppState>,
+    Path((tenant_id, module_id)): Path<(String, String)>,
+) -> Result<Json<ApiResponse<Option<module_service::CapabilityGrant>>>, ApiError> {
+    match state.runtime.get_capability_grant(&tenant_id, &module_id).await {
+        Ok(grant) => Ok(Json(ApiResponse::success(grant))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
 // Marketplace handlers
 
 async fn search_marketplace(
     State(state): State<AppState>,
-    Json(query): Json<ModuleSearchQuery>,
-) -> Result<Json<ApiResponse<module_service::ModuleSearchResult>>, ApiError> {
+    Json(query): Json<MarketplaceSearchQuery>,
+) -> Result<Json<ApiResponse<module_service::MarketplaceSearchResult>>, ApiError> {
     match state.runtime.search_marketplace(&query).await {
         Ok(result) => Ok(Json(ApiResponse::success(result))),
         Err(e) => Err(ApiError::from(e)),
     }
 }
 
+async fn recommend_modules(
+    State(state): State<AppState>,
+    Json(request): Json<RecommendationRequest>,
+) -> Result<Json<ApiResponse<Vec<module_service::marketplace::ModuleRecommendation>>>, ApiError> {
+    match state.runtime.recommend_modules(&request).await {
+        Ok(recommendations) => Ok(Json(ApiResponse::success(recommendations))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
 async fn get_marketplace_module(
     State(state): State<AppState>,
     Path(module_id): Path<String>,
@@ -279,6 +387,319 @@ async fn submit_module_review(
     }
 }
 
+// Rollout handlers
+
+#[derive(Debug, Deserialize)]
+struct RolloutQuery {
+    module_id: String,
+}
+
+async fn start_rollout(
+    State(state): State<AppState>,
+    Json(request): Json<module_service::StartRolloutRequest>,
+) -> Result<Json<ApiResponse<module_service::ModuleRollout>>, ApiError> {
+    match state.runtime.start_rollout(request).await {
+        Ok(rollout) => Ok(Json(ApiResponse::success(rollout))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+async fn list_module_rollouts(
+    State(state): State<AppState>,
+    Query(query): Query<RolloutQuery>,
+) -> Result<Json<ApiResponse<Vec<module_service::ModuleRollout>>>, ApiError> {
+    match state.runtime.list_module_rollouts(&query.module_id).await {
+        Ok(rollouts) => Ok(Json(ApiResponse::success(rollouts))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+async fn get_rollout(
+    State(state): State<AppState>,
+    Path(rollout_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<module_service::ModuleRollout>>, ApiError> {
+    match state.runtime.get_rollout(rollout_id).await {
+        Ok(rollout) => Ok(Json(ApiResponse::success(rollout))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+async fn pause_rollout(
+    State(state): State<AppState>,
+    Path(rollout_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<module_service::ModuleRollout>>, ApiError> {
+    match state.runtime.pause_rollout(rollout_id).await {
+        Ok(rollout) => Ok(Json(ApiResponse::success(rollout))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+async fn resume_rollout(
+    State(state): State<AppState>,
+    Path(rollout_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<module_service::ModuleRollout>>, ApiError> {
+    match state.runtime.resume_rollout(rollout_id).await {
+        Ok(rollout) => Ok(Json(ApiResponse::success(rollout))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+async fn rollback_rollout(
+    State(state): State<AppState>,
+    Path(rollout_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<module_service::ModuleRollout>>, ApiError> {
+    match state.runtime.rollback_rollout(rollout_id).await {
+        Ok(rollout) => Ok(Json(ApiResponse::success(rollout))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+// Package signing handlers
+
+async fn register_developer_key(
+    State(state): State<AppState>,
+    Json(request): Json<module_service::RegisterDeveloperKeyRequest>,
+) -> Result<Json<ApiResponse<module_service::DeveloperKey>>, ApiError> {
+    match state.runtime.register_developer_key(request).await {
+        Ok(key) => Ok(Json(ApiResponse::success(key))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+async fn get_developer_key(
+    State(state): State<AppState>,
+    Path(key_id): Path<String>,
+) -> Result<Json<ApiResponse<module_service::DeveloperKey>>, ApiError> {
+    match state.runtime.get_developer_key(&key_id).await {
+        Ok(key) => Ok(Json(ApiResponse::success(key))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+async fn revoke(
+    State(state): State<AppState>,
+    Json(request): Json<module_service::RevokeRequest>,
+) -> Result<Json<ApiResponse<module_service::Revocation>>, ApiError> {
+    match state.runtime.revoke(request).await {
+        Ok(revocation) => Ok(Json(ApiResponse::success(revocation))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+async fn list_revocations(
+    State(state): State<AppState>,
+) -> Result<Json<ApiResponse<Vec<module_service::Revocation>>>, ApiError> {
+    match state.runtime.list_revocations().await {
+        Ok(revocations) => Ok(Json(ApiResponse::success(revocations))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+// Event bus handlers
+
+async fn publish_platform_event(
+    State(state): State<AppState>,
+    Json(request): Json<module_service::PublishPlatformEventRequest>,
+) -> Result<Json<ApiResponse<module_service::BusEvent>>, ApiError> {
+    match state.runtime.publish_platform_event(request).await {
+        Ok(event) => Ok(Json(ApiResponse::success(event))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+async fn list_dead_letters(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<ApiResponse<Vec<module_service::EventDelivery>>>, ApiError> {
+    match state.runtime.list_dead_letters(&tenant_id).await {
+        Ok(deliveries) => Ok(Json(ApiResponse::success(deliveries))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+async fn retry_dead_letter(
+    State(state): State<AppState>,
+    Path((tenant_id, delivery_id)): Path<(String, Uuid)>,
+) -> Result<Json<ApiResponse<()>>, ApiError> {
+    match state.runtime.retry_dead_letter(&tenant_id, delivery_id).await {
+        Ok(_) => Ok(Json(ApiResponse::success(()))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct UsageQuery {
+    from: Option<chrono::DateTime<chrono::Utc>>,
+    to: Option<chrono::DateTime<chrono::Utc>>,
+}
+
+async fn get_tenant_usage(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<String>,
+    Query(query): Query<UsageQuery>,
+) -> Result<Json<ApiResponse<module_service::TenantUsageReport>>, ApiError> {
+    // Defaults to the last 30 days
+    let to = query.to.unwrap_or_else(chrono::Utc::now);
+    let from = query.from.unwrap_or(to - chrono::Duration::days(30));
+    match state.runtime.tenant_usage(&tenant_id, from, to).await {
+        Ok(report) => Ok(Json(ApiResponse::success(report))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+// Security review handlers
+
+#[derive(Debug, Deserialize)]
+struct ScanRequest {
+    module_id: String,
+    version: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReviewQuery {
+    status: Option<module_service::ReviewStatus>,
+}
+
+async fn scan_marketplace_module(
+    State(state): State<AppState>,
+    Json(request): Json<ScanRequest>,
+) -> Result<Json<ApiResponse<module_service::SecurityReport>>, ApiError> {
+    match state.runtime.scan_marketplace_module(&request.module_id, &request.version).await {
+        Ok(report) => Ok(Json(ApiResponse::success(report))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+async fn get_security_report(
+    State(state): State<AppState>,
+    Path(scan_id): Path<Uuid>,
+) -> Result<Json<ApiResponse<module_service::SecurityReport>>, ApiError> {
+    match state.runtime.get_security_report(scan_id).await {
+        Ok(report) => Ok(Json(ApiResponse::success(report))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+async fn list_security_reviews(
+    State(state): State<AppState>,
+    Query(query): Query<ReviewQuery>,
+) -> Result<Json<ApiResponse<Vec<module_service::SecurityReport>>>, ApiError> {
+    // Defaults to the review queue
+    let status = query.status.unwrap_or(module_service::ReviewStatus::Pending);
+    match state.runtime.list_security_reports(status).await {
+        Ok(reports) => Ok(Json(ApiResponse::success(reports))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+async fn review_security_report(
+    State(state): State<AppState>,
+    Path(scan_id): Path<Uuid>,
+    Json(decision): Json<module_service::ReviewDecision>,
+) -> Result<Json<ApiResponse<module_service::SecurityReport>>, ApiError> {
+    match state.runtime.review_security_report(scan_id, decision).await {
+        Ok(report) => Ok(Json(ApiResponse::success(report))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+// Module job handlers
+
+async fn list_module_jobs(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<ApiResponse<Vec<module_service::ModuleJob>>>, ApiError> {
+    match state.runtime.list_module_jobs(&tenant_id).await {
+        Ok(jobs) => Ok(Json(ApiResponse::success(jobs))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+async fn list_module_job_runs(
+    State(state): State<AppState>,
+    Path((tenant_id, job_id)): Path<(String, Uuid)>,
+) -> Result<Json<ApiResponse<Vec<module_service::JobRun>>>, ApiError> {
+    match state.runtime.list_module_job_runs(&tenant_id, job_id).await {
+        Ok(runs) => Ok(Json(ApiResponse::success(runs))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+/// Called by the workflow-service schedule of the job
+async fn run_module_job(
+    State(state): State<AppState>,
+    Path((tenant_id, job_id)): Path<(String, Uuid)>,
+) -> Result<Json<ApiResponse<module_service::JobRun>>, ApiError> {
+    match state.runtime.run_module_job(&tenant_id, job_id).await {
+        Ok(run) => Ok(Json(ApiResponse::success(run))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+async fn disable_module_job(
+    State(state): State<AppState>,
+    Path((tenant_id, job_id)): Path<(String, Uuid)>,
+    Json(request): Json<module_service::DisableJobRequest>,
+) -> Result<Json<ApiResponse<module_service::ModuleJob>>, ApiError> {
+    match state.runtime.disable_module_job(&tenant_id, job_id, request).await {
+        Ok(job) => Ok(Json(ApiResponse::success(job))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+async fn enable_module_job(
+    State(state): State<AppState>,
+    Path((tenant_id, job_id)): Path<(String, Uuid)>,
+) -> Result<Json<ApiResponse<module_service::ModuleJob>>, ApiError> {
+    match state.runtime.enable_module_job(&tenant_id, job_id).await {
+        Ok(job) => Ok(Json(ApiResponse::success(job))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
+// Frontend asset handlers
+
+async fn get_module_asset(
+    State(state): State<AppState>,
+    Path((module_id, version, path)): Path<(String, String, String)>,
+    headers: HeaderMap,
+) -> Result<Response, ApiError> {
+    let asset = state.runtime.get_module_asset(&module_id, &version, &path).await
+        .map_err(ApiError::from)?;
+    let etag = format!("\"{}\"", asset.etag);
+    let cache_control = state.runtime.asset_cache_control();
+
+    let not_modified = headers.get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .map(|value| value.split(',').any(|tag| tag.trim() == etag || tag.trim() == "*"))
+        .unwrap_or(false);
+    if not_modified {
+        return Ok((
+            StatusCode::NOT_MODIFIED,
+            [(header::ETAG, etag), (header::CACHE_CONTROL, cache_control)],
+        ).into_response());
+    }
+
+    Ok((
+        [
+            (header::CONTENT_TYPE, asset.content_type),
+            (header::ETAG, etag),
+            (header::CACHE_CONTROL, cache_control),
+        ],
+        asset.content,
+    ).into_response())
+}
+
+async fn get_frontend_manifest(
+    State(state): State<AppState>,
+    Path(tenant_id): Path<String>,
+) -> Result<Json<ApiResponse<module_service::FrontendManifest>>, ApiError> {
+    match state.runtime.frontend_manifest(&tenant_id).await {
+        Ok(manifest) => Ok(Json(ApiResponse::success(manifest))),
+        Err(e) => Err(ApiError::from(e)),
+    }
+}
+
 // Workflow handlers
 
 async fn install_module_workflow(
@@ -448,6 +869,7 @@ impl From<ModuleError> for ApiError {
             ModuleError::ValidationFailed(msg) => (StatusCode::BAD_REQUEST, msg),
             ModuleError::PermissionDenied(msg) => (StatusCode::FORBIDDEN, msg),
             ModuleError::SecurityScanFailed(msg) => (StatusCode::BAD_REQUEST, msg),
+            ModuleError::SignatureVerificationFailed(msg) => (StatusCode::BAD_REQUEST, msg),
             ModuleError::PaymentError(msg) => (StatusCode::PAYMENT_REQUIRED, msg),
             ModuleError::NetworkError(msg) => (StatusCode::BAD_GATEWAY, msg),
             _ => (StatusCode::INTERNAL_SERVER_ERROR, err.to_string()),
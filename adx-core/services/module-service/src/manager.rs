@@ -7,10 +7,14 @@ use semver::Version;
 
 use crate::{
     ModuleResult, ModuleError, ModuleInstance, ModulePackage, ModuleManifest,
-    AdxModule, ModuleLoader, ModuleRepository, ModuleSandbox, ModuleSecurityScanner,
+    AdxModule, ModuleLoader, ModuleRepository, ModuleSandbox, SecurityReviewQueue,
     ModuleStatus, InstallModuleRequest, InstallModuleResult, UpdateModuleRequest,
     UpdateModuleResult, UninstallModuleRequest, UninstallModuleResult,
     ResourceUsage, HealthStatus, ModuleEvent, ExtensionContext,
+    CapabilityConsent, CapabilityGrant, CapabilityGrants, ConsentScreen, PackageVerifier,
+    EventBus, ModuleHost, ModuleConfiguration, ModuleSettings, ModuleSettingsView,
+    UpdateSettingsRequest, settings, UsageMeter, ModuleAssetStore,
+    LifecycleHookRunner, HookPhase, HookContext, ModuleJobScheduler, ModuleJob,
 };
 
 /// Comprehensive module manager with hot-loading and lifecycle management
@@ -27,14 +31,29 @@ pub struct ModuleManager {
     /// Module sandbox for isolation
     sandbox: Arc<dyn ModuleSandbox>,
     
-    /// Security scanner
-    security_scanner: Arc<dyn ModuleSecurityScanner>,
+    /// Security scanning and review of packages
+    security_reviews: Arc<SecurityReviewQueue>,
+    
+    /// Package checksum and signature verifier
+    verifier: Arc<PackageVerifier>,
     
     /// Dependency resolver
     dependency_resolver: Arc<DependencyResolver>,
     
     /// Event bus for module communication
-    event_bus: Arc<ModuleEventBus>,
+    event_bus: Arc<EventBus>,
+    
+    /// Usage meter for billing
+    meter: Arc<UsageMeter>,
+    
+    /// Frontend asset store
+    assets: Arc<ModuleAssetStore>,
+    
+    /// Runner of modules' install, upgrade and uninstall hooks
+    hooks: Arc<LifecycleHookRunner>,
+    
+    /// Background jobs modules registered
+    jobs: Arc<ModuleJobScheduler>,
     
     /// Resource monitor
     resource_monitor: Arc<ResourceMonitor>,
@@ -74,7 +93,13 @@ impl ModuleManager {
     pub fn new(
         repository: Arc<dyn ModuleRepository>,
         sandbox: Arc<dyn ModuleSandbox>,
-        security_scanner: Arc<dyn ModuleSecurityScanner>,
+        security_reviews: Arc<SecurityReviewQueue>,
+        verifier: Arc<PackageVerifier>,
+        event_bus: Arc<EventBus>,
+        meter: Arc<UsageMeter>,
+        assets: Arc<ModuleAssetStore>,
+        hooks: Arc<LifecycleHookRunner>,
+        jobs: Arc<ModuleJobScheduler>,
         config: ModuleManagerConfig,
     ) -> Self {
         Self {
@@ -82,9 +107,14 @@ impl ModuleManager {
             loaders: Arc::new(RwLock::new(HashMap::new())),
             repository,
             sandbox,
-            security_scanner,
+            security_reviews,
+            verifier,
             dependency_resolver: Arc::new(DependencyResolver::new()),
-            event_bus: Arc::new(ModuleEventBus::new()),
+            event_bus,
+            meter,
+            assets,
+            hooks,
+            jobs,
             resource_monitor: Arc::new(ResourceMonitor::new()),
             config,
         }
@@ -120,31 +150,42 @@ impl ModuleManager {
                     user_id: request.user_id.clone(),
                     configuration: None,
                     auto_activate: false,
+                    // Consent covers one module; dependencies that declare
+                    // capabilities are installed with their own first
+                    consent: None,
                 };
                 self.install_module(dep_request).await?;
             }
         }
 
-        // Step 4: Download and validate module package
+        // Step 4: Download and validate module package, and verify its
+        // checksum and signatures
         let package = self.download_and_validate_package(&request).await?;
+        self.verifier.verify(&package).await?;
 
-        // Step 5: Security scan
+        // Step 5: Security scan; packages the scan holds back install once
+        // a reviewer approves them
         if self.config.security_scanning_enabled {
-            let scan_result = self.security_scanner.scan_package(&package).await?;
-            if !scan_result.issues.is_empty() {
-                let critical_issues: Vec<_> = scan_result.issues.iter()
-                    .filter(|issue| matches!(issue.severity, crate::Severity::Critical))
-                    .collect();
-                
-                if !critical_issues.is_empty() {
-                    return Err(ModuleError::SecurityScanFailed(
-                        format!("Critical security issues found: {}", critical_issues.len())
-                    ));
-                }
-            }
+            self.security_reviews.admit(&package).await?;
         }
-
-        // Step 6: Create module instance
+        let event_schemas = EventBus::compile_schemas(&package.metadata.id, &package.manifest.published_events)?;
+        self.assets.validate_package(&package)?;
+
+        // Step 6: Validate the tenant's settings against the module's schema
+        let configuration = request.configuration.clone().unwrap_or_default();
+        settings::check_tenant_keys(&package.manifest.configuration, &configuration)?;
+        let configuration = settings::merge_settings(&package.manifest.configuration.default_config, &configuration);
+        settings::validate_settings(&package.manifest.configuration, &configuration)?;
+
+        // Step 7: Grant the capabilities the tenant admin consented to
+        let grants = self.grant_capabilities(
+            &request.tenant_id,
+            &package,
+            None,
+            request.consent.as_ref(),
+        ).await?;
+
+        // Step 8: Create module instance
         let instance_id = Uuid::new_v4();
         let instance = ModuleInstance {
             id: instance_id,
@@ -152,7 +193,7 @@ impl ModuleManager {
             tenant_id: request.tenant_id.clone(),
             version: package.metadata.version.clone(),
             status: crate::ModuleStatus::Installing,
-            configuration: request.configuration.unwrap_or_default(),
+            configuration,
             installation_path: format!("/modules/{}/{}", request.tenant_id, instance_id),
             installed_at: chrono::Utc::now(),
             activated_at: None,
@@ -176,33 +217,72 @@ impl ModuleManager {
             },
         };
 
-        // Step 7: Save instance to repository
+        // Step 9: Save instance, its first settings version and the
+        // version's frontend assets to repository
         self.repository.save_instance(&instance).await?;
+        self.assets.store_package(&package).await?;
+        self.record_settings(instance_id, 1, instance.configuration.clone(), &request.user_id).await?;
 
-        // Step 8: Load module using appropriate loader
-        let module = self.load_module_with_loader(&package).await?;
+        // Step 10: Run the module's install hook, e.g. to create its schema.
+        // A failed hook has compensated itself; the instance is removed.
+        let hook_context = HookContext {
+            phase: HookPhase::Install,
+            instance_id,
+            tenant_id: request.tenant_id.clone(),
+            module_id: request.module_id.clone(),
+            from_version: None,
+            to_version: Some(package.metadata.version.clone()),
+            cleanup_data: false,
+            compensating: false,
+        };
+        if let Err(e) = self.hooks.run(&package.manifest, &hook_context).await {
+            self.rollback_install(&instance).await;
+            return Err(e);
+        }
 
-        // Step 9: Initialize module
-        let mut module_guard = module.write().await;
-        module_guard.initialize(instance.configuration.clone()).await?;
+        // Steps 11-15 undo the hook and the instance if they fail
+        let installed: ModuleResult<()> = async {
+            // Step 11: Load module using appropriate loader
+            let module = self.load_module_with_loader(&package).await?;
+
+            // Step 12: Bind to the host API and initialize module
+            self.event_bus.register_instance(instance_id, event_schemas).await;
+            {
+                let mut module_guard = module.write().await;
+                module_guard.bind_host(self.module_host(instance_id, &request.tenant_id, grants));
+                module_guard.initialize(instance.configuration.clone()).await?;
+            }
 
-        // Step 10: Store in active instances
-        {
-            let mut instances = self.instances.write().await;
-            instances.insert(instance_id, module);
-        }
+            // Step 13: Store in active instances
+            {
+                let mut instances = self.instances.write().await;
+                instances.insert(instance_id, module);
+            }
 
-        // Step 11: Update status to installed
-        self.repository.update_instance_status(instance_id, crate::ModuleStatus::Installed).await?;
+            // Step 14: Update status to installed
+            self.repository.update_instance_status(instance_id, crate::ModuleStatus::Installed).await?;
 
-        // Step 12: Auto-activate if requested
-        if request.auto_activate {
-            self.activate_module(instance_id).await?;
+            // Step 15: Auto-activate if requested
+            if request.auto_activate {
+                self.activate_module(instance_id).await?;
+            }
+            Ok(())
+        }.await;
+        if let Err(e) = installed {
+            self.hooks.compensate(&package.manifest, &hook_context).await;
+            self.rollback_install(&instance).await;
+            return Err(e);
         }
 
-        // Step 13: Start monitoring
+        // Step 16: Start monitoring
         self.start_monitoring(instance_id).await?;
 
+        self.publish_platform_event(&request.tenant_id, "platform.module.installed", serde_json::json!({
+            "module_id": request.module_id,
+            "instance_id": instance_id,
+            "version": package.metadata.version.to_string(),
+        })).await;
+
         info!("Successfully installed module: {} ({})", request.module_id, instance_id);
 
         Ok(InstallModuleResult {
@@ -298,9 +378,36 @@ impl ModuleManager {
 
         // Download new version
         let package = self.download_package(&instance.module_id, &target_version).await?;
+        self.verifier.verify(&package).await?;
+        if self.config.security_scanning_enabled {
+            self.security_reviews.admit(&package).await?;
+        }
+        let event_schemas = EventBus::compile_schemas(&package.metadata.id, &package.manifest.published_events)?;
 
         // Validate compatibility
         self.validate_update_compatibility(&instance, &package).await?;
+        self.assets.validate_package(&package)?;
+
+        // New capabilities need the tenant admin's consent
+        let current_grant = self.repository
+            .get_capability_grant(&instance.module_id, &instance.tenant_id)
+            .await?;
+        let grants = self.grant_capabilities(
+            &instance.tenant_id,
+            &package,
+            current_grant.as_ref(),
+            request.consent.as_ref(),
+        ).await?;
+
+        // Carry the settings over to the new version's defaults, if
+        // requested, and check them against its schema
+        let current_settings = self.current_settings(&instance).await?;
+        let config = if request.preserve_config {
+            settings::merge_settings(&package.manifest.configuration.default_config, &current_settings.values)
+        } else {
+            package.manifest.configuration.default_config.clone()
+        };
+        settings::validate_settings(&package.manifest.configuration, &config)?;
 
         // Deactivate current module
         if matches!(instance.status, crate::ModuleStatus::Active) {
@@ -310,34 +417,64 @@ impl ModuleManager {
         // Update status to updating
         self.repository.update_instance_status(request.instance_id, crate::ModuleStatus::Updating).await?;
 
-        // Load new module version
-        let new_module = self.load_module_with_loader(&package).await?;
-
-        // Preserve configuration if requested
-        let config = if request.preserve_config {
-            instance.configuration.clone()
-        } else {
-            serde_json::Value::Null
+        // Run the new version's upgrade hook, e.g. to migrate its schema. A
+        // failed hook has compensated itself; the old version is restored.
+        let hook_context = HookContext {
+            phase: HookPhase::Upgrade,
+            instance_id: request.instance_id,
+            tenant_id: instance.tenant_id.clone(),
+            module_id: instance.module_id.clone(),
+            from_version: Some(old_version.clone()),
+            to_version: Some(target_version.clone()),
+            cleanup_data: false,
+            compensating: false,
         };
-
-        // Initialize new module
-        {
-            let mut module_guard = new_module.write().await;
-            module_guard.initialize(config).await?;
+        if let Err(e) = self.hooks.run(&package.manifest, &hook_context).await {
+            self.restore_instance(&instance).await;
+            return Err(e);
         }
 
-        // Replace in active instances
-        {
-            let mut instances = self.instances.write().await;
-            instances.insert(request.instance_id, new_module);
-        }
+        // Loading the new version undoes the hook if it fails
+        let updated: ModuleResult<ModuleInstance> = async {
+            // Load new module version, storing its frontend assets first so
+            // the shell finds them once the instance reports the new version
+            self.assets.store_package(&package).await?;
+            let new_module = self.load_module_with_loader(&package).await?;
+
+            // Initialize new module; it subscribes again
+            self.event_bus.release_instance(request.instance_id).await;
+            self.event_bus.register_instance(request.instance_id, event_schemas).await;
+            {
+                let mut module_guard = new_module.write().await;
+                module_guard.bind_host(self.module_host(request.instance_id, &instance.tenant_id, grants));
+                module_guard.initialize(config.clone()).await?;
+            }
 
-        // Update instance record
-        let mut updated_instance = instance;
-        updated_instance.version = target_version.clone();
-        updated_instance.status = crate::ModuleStatus::Installed;
-        updated_instance.last_updated = chrono::Utc::now();
-        self.repository.save_instance(&updated_instance).await?;
+            // Replace in active instances
+            {
+                let mut instances = self.instances.write().await;
+                instances.insert(request.instance_id, new_module);
+            }
+
+            // Update instance record
+            let mut updated_instance = instance.clone();
+            updated_instance.version = target_version.clone();
+            updated_instance.status = crate::ModuleStatus::Installed;
+            updated_instance.last_updated = chrono::Utc::now();
+            self.repository.save_instance(&updated_instance).await?;
+            if config != current_settings.values {
+                self.record_settings(request.instance_id, current_settings.version + 1, config, "system").await?;
+            }
+            Ok(updated_instance)
+        }.await;
+        let updated_instance = match updated {
+            Ok(updated_instance) => updated_instance,
+            Err(e) => {
+                self.hooks.compensate(&package.manifest, &hook_context).await;
+                self.restore_instance(&instance).await;
+                return Err(e);
+            }
+        };
 
         // Reactivate if it was active before
         if matches!(updated_instance.status, crate::ModuleStatus::Active) {
@@ -379,6 +516,25 @@ impl ModuleManager {
         // Update status to uninstalling
         self.repository.update_instance_status(request.instance_id, crate::ModuleStatus::Uninstalling).await?;
 
+        // Run the module's uninstall hook, e.g. to export or drop its data,
+        // while the module is still loaded. A failed hook has compensated
+        // itself; the instance is left as it was.
+        let manifest = self.installed_manifest(&instance).await?;
+        let hook_context = HookContext {
+            phase: HookPhase::Uninstall,
+            instance_id: request.instance_id,
+            tenant_id: instance.tenant_id.clone(),
+            module_id: instance.module_id.clone(),
+            from_version: Some(instance.version.clone()),
+            to_version: None,
+            cleanup_data: request.cleanup_data,
+            compensating: false,
+        };
+        if let Err(e) = self.hooks.run(&manifest, &hook_context).await {
+            self.restore_instance(&instance).await;
+            return Err(e);
+        }
+
         // Shutdown module
         if let Some(module) = self.instances.read().await.get(&request.instance_id) {
             let mut module_guard = module.write().await;
@@ -390,12 +546,27 @@ impl ModuleManager {
             let mut instances = self.instances.write().await;
             instances.remove(&request.instance_id);
         }
-
-        // Cleanup resources
-        let cleanup_summary = self.cleanup_module_resources(request.instance_id, request.cleanup_data).await?;
+        self.event_bus.release_instance(request.instance_id).await;
+
+        // Cleanup resources; the hook is undone if they cannot be
+        let cleanup_summary = match self.cleanup_module_resources(request.instance_id, request.cleanup_data).await {
+            Ok(cleanup_summary) => cleanup_summary,
+            Err(e) => {
+                self.hooks.compensate(&manifest, &hook_context).await;
+                let _ = self.repository.update_instance_status(request.instance_id, crate::ModuleStatus::Failed).await;
+                return Err(e);
+            }
+        };
 
         // Remove from repository
+        self.jobs.release_instance(request.instance_id).await?;
         self.repository.delete_instance(request.instance_id).await?;
+        self.repository.delete_capability_grant(&instance.module_id, &instance.tenant_id).await?;
+
+        self.publish_platform_event(&instance.tenant_id, "platform.module.uninstalled", serde_json::json!({
+            "module_id": instance.module_id,
+            "instance_id": request.instance_id,
+        })).await;
 
         info!("Successfully uninstalled module: {}", request.instance_id);
 
@@ -423,9 +594,14 @@ impl ModuleManager {
         // Load new module instance
         let new_module = self.load_module_with_loader(&package).await?;
 
-        // Initialize with current configuration
+        // Initialize with current configuration and grant; it subscribes again
+        let grants = self.host_grants(&instance.module_id, &instance.tenant_id).await?;
+        let event_schemas = EventBus::compile_schemas(&package.metadata.id, &package.manifest.published_events)?;
+        self.event_bus.release_instance(instance_id).await;
+        self.event_bus.register_instance(instance_id, event_schemas).await;
         {
             let mut module_guard = new_module.write().await;
+            module_guard.bind_host(self.module_host(instance_id, &instance.tenant_id, grants));
             module_guard.initialize(instance.configuration.clone()).await?;
             
             // Start if the old module was active
@@ -493,6 +669,175 @@ impl ModuleManager {
         Ok(())
     }
 
+    /// Current settings of an instance, with the schema its module declares
+    pub async fn get_settings(&self, instance_id: Uuid) -> ModuleResult<ModuleSettingsView> {
+        let instance = self.repository.get_instance(instance_id).await?
+            .ok_or_else(|| ModuleError::NotFound(instance_id.to_string()))?;
+        let configuration = self.module_configuration(&instance).await?;
+
+        Ok(ModuleSettingsView {
+            current: self.current_settings(&instance).await?,
+            schema: configuration.config_schema,
+            defaults: configuration.default_config,
+            required: configuration.required_config,
+            tenant_configurable: configuration.tenant_configurable,
+        })
+    }
+
+    /// Settings versions of an instance, newest first
+    pub async fn list_settings_versions(&self, instance_id: Uuid) -> ModuleResult<Vec<ModuleSettings>> {
+        self.repository.list_settings_versions(instance_id).await
+    }
+
+    /// Change a tenant's settings of a module instance. The change is
+    /// validated, stored as a new version and applied to the running
+    /// instance, which gets a `ConfigurationChanged` event per changed key.
+    pub async fn update_settings(&self, instance_id: Uuid, request: UpdateSettingsRequest) -> ModuleResult<ModuleSettings> {
+        let instance = self.repository.get_instance(instance_id).await?
+            .ok_or_else(|| ModuleError::NotFound(instance_id.to_string()))?;
+        let configuration = self.module_configuration(&instance).await?;
+        let current = self.current_settings(&instance).await?;
+
+        if let Some(expected_version) = request.expected_version {
+            if expected_version != current.version {
+                return Err(ModuleError::AlreadyExists(format!(
+                    "Settings of {} are at version {}, not {}", instance_id, current.version, expected_version
+                )));
+            }
+        }
+
+        let changes = serde_json::Value::Object(request.values);
+        settings::check_tenant_keys(&configuration, &changes)?;
+        let values = settings::merge_settings(&current.values, &changes);
+        settings::validate_settings(&configuration, &values)?;
+
+        let module = self.instances.read().await.get(&instance_id).cloned();
+        if let Some(module) = &module {
+            module.read().await.validate_config(&values)?;
+        }
+
+        let updated = self.record_settings(instance_id, current.version + 1, values, &request.updated_by).await?;
+        info!("Updated settings of module {} to version {}", instance_id, updated.version);
+
+        // A running instance that fails to apply the change picks it up
+        // when it is next initialized
+        if let Some(module) = module {
+            let mut module_guard = module.write().await;
+            if let Err(e) = module_guard.configure(updated.values.clone()).await {
+                warn!("Module {} failed to apply settings version {}: {}", instance_id, updated.version, e);
+            }
+            for (key, old_value, new_value) in settings::changed_settings(&current.values, &updated.values) {
+                let event = ModuleEvent::ConfigurationChanged { key, old_value, new_value };
+                if let Err(e) = module_guard.handle_event(event).await {
+                    warn!("Module {} failed to handle a settings change: {}", instance_id, e);
+                }
+            }
+        }
+
+        Ok(updated)
+    }
+
+    /// Meter the resource usage of every loaded instance over the last
+    /// `seconds`
+    pub async fn meter_usage(&self, seconds: f64) -> ModuleResult<()> {
+        let instances: Vec<(Uuid, Arc<RwLock<Box<dyn AdxModule>>>)> = self.instances.read().await
+            .iter()
+            .map(|(instance_id, module)| (*instance_id, module.clone()))
+            .collect();
+
+        for (instance_id, module) in instances {
+            let Some(instance) = self.repository.get_instance(instance_id).await? else {
+                continue;
+            };
+            let usage = match module.read().await.resource_usage().await {
+                Ok(usage) => usage,
+                Err(e) => {
+                    warn!("Failed to measure resource usage of module {}: {}", instance_id, e);
+                    continue;
+                }
+            };
+            self.meter.record_sample(instance_id, &instance.module_id, &instance.tenant_id, &usage, seconds).await?;
+        }
+
+        Ok(())
+    }
+
+    /// Hand due event bus deliveries to their subscribers. A delivery that
+    /// fails is retried with backoff until it is dead-lettered.
+    pub async fn deliver_pending_events(&self, limit: u32) -> ModuleResult<usize> {
+        let deliveries = self.event_bus.due_deliveries(limit).await?;
+        let count = deliveries.len();
+
+        for delivery in deliveries {
+            let module = self.instances.read().await.get(&delivery.instance_id).cloned();
+            let result = match module {
+                Some(module) => {
+                    let mut module_guard = module.write().await;
+                    module_guard.handle_event(ModuleEvent::Custom {
+                        event_type: delivery.event.event_type.clone(),
+                        data: delivery.event.data.clone(),
+                    }).await
+                }
+                // Uninstalled since the event was published
+                None if self.repository.get_instance(delivery.instance_id).await?.is_none() => Ok(()),
+                None => Err(ModuleError::RuntimeError(format!("Module {} is not loaded", delivery.instance_id))),
+            };
+
+            match result {
+                Ok(()) => self.event_bus.delivered(delivery.id).await?,
+                Err(e) => self.event_bus.delivery_failed(&delivery, &e.to_string()).await?,
+            }
+        }
+
+        Ok(count)
+    }
+
+    /// Hand a run of a background job to the module that registered it
+    pub async fn dispatch_job(&self, job: &ModuleJob, run_id: Uuid) -> ModuleResult<()> {
+        let module = self.instances.read().await.get(&job.instance_id).cloned()
+            .ok_or_else(|| ModuleError::RuntimeError(format!("Module {} is not loaded", job.instance_id)))?;
+        let mut module_guard = module.write().await;
+        module_guard.handle_event(ModuleEvent::JobTriggered {
+            job: job.name.clone(),
+            run_id,
+            payload: job.payload.clone(),
+        }).await
+    }
+
+    /// What a tenant admin consents to before installing or updating to a
+    /// module package
+    pub async fn consent_screen(&self, tenant_id: &str, package: &ModulePackage) -> ModuleResult<ConsentScreen> {
+        let manifest = &package.manifest;
+        manifest.required_capabilities.validate()?;
+
+        // Installing asks for everything again, even if an earlier install
+        // left a grant behind
+        let current_grant = if self.is_module_installed(&manifest.metadata.id, tenant_id).await? {
+            self.repository.get_capability_grant(&manifest.metadata.id, tenant_id).await?
+        } else {
+            None
+        };
+        let newly_requested = match current_grant {
+            Some(grant) => manifest.required_capabilities.missing_from(&grant.capabilities),
+            None => manifest.required_capabilities.clone(),
+        };
+
+        Ok(ConsentScreen {
+            module_id: manifest.metadata.id.clone(),
+            module_name: manifest.metadata.name.clone(),
+            version: manifest.metadata.version.clone(),
+            author: manifest.metadata.author.name.clone(),
+            capabilities: manifest.required_capabilities.clone(),
+            descriptions: manifest.required_capabilities.describe(),
+            newly_requested,
+        })
+    }
+
+    /// The capabilities a tenant granted a module
+    pub async fn get_capability_grant(&self, module_id: &str, tenant_id: &str) -> ModuleResult<Option<CapabilityGrant>> {
+        self.repository.get_capability_grant(module_id, tenant_id).await
+    }
+
     // Private helper methods
 
     async fn validate_installation_request(&self, request: &InstallModuleRequest) -> ModuleResult<()> {
@@ -537,6 +882,170 @@ impl ModuleManager {
         Err(ModuleError::RuntimeError("No suitable loader found for module".to_string()))
     }
 
+    /// Record the tenant's grant of the package's capabilities. Whatever the
+    /// current grant does not cover needs the tenant admin's consent.
+    async fn grant_capabilities(
+        &self,
+        tenant_id: &str,
+        package: &ModulePackage,
+        current_grant: Option<&CapabilityGrant>,
+        consent: Option<&CapabilityConsent>,
+    ) -> ModuleResult<CapabilityGrants> {
+        let manifest = &package.manifest;
+        let required = &manifest.required_capabilities;
+        required.validate()?;
+
+        let mut missing = match current_grant {
+            Some(grant) => required.missing_from(&grant.capabilities),
+            None => required.clone(),
+        };
+        if let Some(consent) = consent {
+            missing = missing.missing_from(&consent.capabilities);
+        }
+        if !missing.is_empty() {
+            let capabilities: Vec<String> = missing.describe().into_iter()
+                .map(|description| description.capability)
+                .collect();
+            return Err(ModuleError::PermissionDenied(format!(
+                "Tenant admin consent required for {}: {}",
+                manifest.metadata.id,
+                capabilities.join(", ")
+            )));
+        }
+
+        let granted_by = consent
+            .map(|consent| consent.granted_by.clone())
+            .or_else(|| current_grant.map(|grant| grant.granted_by.clone()))
+            .unwrap_or_default();
+        let grant = CapabilityGrant {
+            module_id: manifest.metadata.id.clone(),
+            tenant_id: tenant_id.to_string(),
+            version: manifest.metadata.version.clone(),
+            capabilities: required.clone(),
+            granted_by,
+            granted_at: chrono::Utc::now(),
+        };
+        self.repository.save_capability_grant(&grant).await?;
+
+        info!("Granted {} capabilities for tenant {}", grant.module_id, tenant_id);
+        Ok(CapabilityGrants::new(&grant.module_id, grant.capabilities))
+    }
+
+    /// The latest settings version; instances installed before settings
+    /// were versioned are at version 0
+    async fn current_settings(&self, instance: &ModuleInstance) -> ModuleResult<ModuleSettings> {
+        match self.repository.get_latest_settings(instance.id).await? {
+            Some(settings) => Ok(settings),
+            None => Ok(ModuleSettings {
+                instance_id: instance.id,
+                version: 0,
+                values: instance.configuration.clone(),
+                updated_by: "system".to_string(),
+                created_at: instance.installed_at,
+            }),
+        }
+    }
+
+    async fn record_settings(
+        &self,
+        instance_id: Uuid,
+        version: u32,
+        values: serde_json::Value,
+        updated_by: &str,
+    ) -> ModuleResult<ModuleSettings> {
+        let settings = ModuleSettings {
+            instance_id,
+            version,
+            values,
+            updated_by: updated_by.to_string(),
+            created_at: chrono::Utc::now(),
+        };
+        if !self.repository.save_settings_version(&settings).await? {
+            return Err(ModuleError::AlreadyExists(
+                format!("Settings of {} changed concurrently; version {} exists", instance_id, version)
+            ));
+        }
+        Ok(settings)
+    }
+
+    /// Settings schema of the module version an instance runs
+    async fn module_configuration(&self, instance: &ModuleInstance) -> ModuleResult<ModuleConfiguration> {
+        if let Some(module) = self.instances.read().await.get(&instance.id) {
+            return Ok(module.read().await.manifest().configuration.clone());
+        }
+        let package = self.download_package(&instance.module_id, &instance.version).await?;
+        Ok(package.manifest.configuration)
+    }
+
+    /// Manifest of the module version an instance runs
+    async fn installed_manifest(&self, instance: &ModuleInstance) -> ModuleResult<ModuleManifest> {
+        if let Some(module) = self.instances.read().await.get(&instance.id) {
+            return Ok(module.read().await.manifest().clone());
+        }
+        let package = self.download_package(&instance.module_id, &instance.version).await?;
+        Ok(package.manifest)
+    }
+
+    /// Remove what a failed install left behind. Best effort: the install's
+    /// own error is what the caller sees.
+    async fn rollback_install(&self, instance: &ModuleInstance) {
+        self.instances.write().await.remove(&instance.id);
+        self.event_bus.release_instance(instance.id).await;
+        if let Err(e) = self.jobs.release_instance(instance.id).await {
+            warn!("Failed to remove the jobs of failed install {}: {}", instance.id, e);
+        }
+        if let Err(e) = self.repository.delete_instance(instance.id).await {
+            warn!("Failed to remove instance {} of a failed install: {}", instance.id, e);
+        }
+        if let Err(e) = self.repository.delete_capability_grant(&instance.module_id, &instance.tenant_id).await {
+            warn!("Failed to remove the grant of failed install {}: {}", instance.id, e);
+        }
+    }
+
+    /// Put an instance back in the state it had before a failed update or
+    /// uninstall, starting it again if it was active
+    async fn restore_instance(&self, instance: &ModuleInstance) {
+        let restored = async {
+            if matches!(instance.status, crate::ModuleStatus::Active) {
+                self.repository.update_instance_status(instance.id, crate::ModuleStatus::Installed).await?;
+                self.activate_module(instance.id).await
+            } else {
+                self.repository.update_instance_status(instance.id, instance.status.clone()).await
+            }
+        }.await;
+        if let Err(e) = restored {
+            error!("Failed to restore instance {}: {}", instance.id, e);
+            let _ = self.repository.update_instance_status(instance.id, crate::ModuleStatus::Failed).await;
+        }
+    }
+
+    fn module_host(&self, instance_id: Uuid, tenant_id: &str, grants: CapabilityGrants) -> ModuleHost {
+        ModuleHost {
+            instance_id,
+            tenant_id: tenant_id.to_string(),
+            grants,
+            events: self.event_bus.clone(),
+            meter: self.meter.clone(),
+            jobs: self.jobs.clone(),
+        }
+    }
+
+    /// Lifecycle events are best effort; failing to publish one does not
+    /// fail the operation
+    async fn publish_platform_event(&self, tenant_id: &str, event_type: &str, data: serde_json::Value) {
+        if let Err(e) = self.event_bus.publish_platform_event(tenant_id, event_type, data).await {
+            warn!("Failed to publish {} for tenant {}: {}", event_type, tenant_id, e);
+        }
+    }
+
+    /// The runtime check of a module's stored grant; nothing when there is none
+    async fn host_grants(&self, module_id: &str, tenant_id: &str) -> ModuleResult<CapabilityGrants> {
+        let capabilities = self.repository.get_capability_grant(module_id, tenant_id).await?
+            .map(|grant| grant.capabilities)
+            .unwrap_or_default();
+        Ok(CapabilityGrants::new(module_id, capabilities))
+    }
+
     async fn validate_update_compatibility(&self, instance: &ModuleInstance, package: &ModulePackage) -> ModuleResult<()> {
         // Validate that the new version is compatible with the current installation
         // Check breaking changes, dependency compatibility, etc.
@@ -589,17 +1098,6 @@ pub struct ResolvedDependency {
     pub optional: bool,
 }
 
-/// Event bus for module communication
-pub struct ModuleEventBus {
-    // Implementation would include event routing and delivery
-}
-
-impl ModuleEventBus {
-    pub fn new() -> Self {
-        Self {}
-    }
-}
-
 /// Resource monitor for tracking module resource usage
 pub struct ResourceMonitor {
     // Implementation would include resource tracking and alerting
@@ -172,6 +172,61 @@ impl ModuleMarketplace {
         Ok(result)
     }
 
+    /// Get the marketplace's revocation list of signing keys and packages
+    pub async fn get_revocations(&self) -> ModuleResult<Vec<crate::Revocation>> {
+        let url = format!("{}/api/v1/revocations", self.config.base_url);
+        
+        let response = self.client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", self.config.api_key))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(ModuleError::MarketplaceError(
+                format!("Failed to get revocations: {}", response.status())
+            ));
+        }
+
+        let revocations: Vec<crate::Revocation> = response.json().await?;
+        Ok(revocations)
+    }
+
+    /// Get a page of the marketplace catalog, optionally only the listings
+    /// changed since a point in time
+    pub async fn get_catalog(
+        &self,
+        updated_since: Option<DateTime<Utc>>,
+        offset: u32,
+        limit: u32,
+    ) -> ModuleResult<CatalogPage> {
+        let url = format!("{}/api/v1/modules/catalog", self.config.base_url);
+        
+        let mut query = vec![
+            ("offset", offset.to_string()),
+            ("limit", limit.to_string()),
+        ];
+        if let Some(updated_since) = updated_since {
+            query.push(("updated_since", updated_since.to_rfc3339()));
+        }
+
+        let response = self.client
+            .get(&url)
+            .header("Authorization", format!("Bearer {}", self.config.api_key))
+            .query(&query)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(ModuleError::MarketplaceError(
+                format!("Failed to fetch catalog: {}", response.status())
+            ));
+        }
+
+        let page: CatalogPage = response.json().await?;
+        Ok(page)
+    }
+
     /// Get module pricing information
     pub async fn get_module_pricing(
         &self,
@@ -826,6 +881,13 @@ pub enum SubmissionStatus {
     RequiresChanges,
 }
 
+/// A page of the marketplace catalog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CatalogPage {
+    pub listings: Vec<crate::search::MarketplaceListing>,
+    pub has_more: bool,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModulePricing {
     pub module_id: String,
@@ -834,6 +896,12 @@ pub struct ModulePricing {
     pub pricing_model: PricingModel,
     pub license_duration: Option<u32>, // days
     pub trial_period: Option<u32>, // days
+    /// Prices per unit of usage, for usage-priced modules
+    #[serde(default)]
+    pub usage_rates: Option<crate::metering::UsageRates>,
+    /// Developer the module's usage revenue is shared with
+    #[serde(default)]
+    pub developer_id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
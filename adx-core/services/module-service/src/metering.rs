@@ -0,0 +1,305 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use chrono::{DateTime, DurationRound, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+use crate::{ModuleResult, ModuleError, ModuleRepository, ResourceUsage};
+use crate::marketplace::{ModuleMarketplace, ModulePricing, PricingModel};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MeteringConfig {
+    pub enabled: bool,
+    /// How often the resource usage of running instances is sampled
+    pub sample_interval_seconds: u64,
+    /// How often closed hours of usage are reported to license-service
+    pub billing_report_interval_seconds: u64,
+    pub license_service_url: String,
+}
+
+impl Default for MeteringConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            sample_interval_seconds: 60,
+            billing_report_interval_seconds: 3600,
+            license_service_url: "http://localhost:8087".to_string(),
+        }
+    }
+}
+
+/// Metered usage of one module instance in one hour
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleUsage {
+    pub instance_id: Uuid,
+    pub module_id: String,
+    pub tenant_id: String,
+    pub period_start: DateTime<Utc>,
+    pub cpu_seconds: f64,
+    pub memory_mb_seconds: f64,
+    pub storage_mb_seconds: f64,
+    /// Host API calls the instance made through the SDK
+    pub api_calls: u64,
+}
+
+/// Usage of a module in a tenant over a period
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct UsageTotals {
+    pub cpu_seconds: f64,
+    pub memory_gb_hours: f64,
+    pub storage_gb_hours: f64,
+    pub api_calls: u64,
+}
+
+impl UsageTotals {
+    fn add(&mut self, usage: &ModuleUsage) {
+        self.cpu_seconds += usage.cpu_seconds;
+        self.memory_gb_hours += usage.memory_mb_seconds / MB_SECONDS_PER_GB_HOUR;
+        self.storage_gb_hours += usage.storage_mb_seconds / MB_SECONDS_PER_GB_HOUR;
+        self.api_calls += usage.api_calls;
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleUsageTotals {
+    pub module_id: String,
+    pub instance_id: Uuid,
+    pub usage: UsageTotals,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantUsageReport {
+    pub tenant_id: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub modules: Vec<ModuleUsageTotals>,
+    pub totals: UsageTotals,
+}
+
+/// Prices per unit of a usage-priced module, in the pricing's currency
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct UsageRates {
+    pub cpu_second: f64,
+    pub memory_gb_hour: f64,
+    pub storage_gb_hour: f64,
+    pub api_call: f64,
+}
+
+/// Usage of a module instance in one hour, as recorded by license-service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleUsageChargeRequest {
+    pub tenant_id: Uuid,
+    pub module_id: String,
+    pub instance_id: Uuid,
+    pub developer_id: String,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub currency: String,
+    pub lines: Vec<ModuleUsageChargeLine>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleUsageChargeLine {
+    pub metric: String,
+    pub quantity: f64,
+    pub unit_price: f64,
+}
+
+const MB_SECONDS_PER_GB_HOUR: f64 = 1024.0 * 3600.0;
+
+/// Meters the CPU, memory, storage and host API calls of module instances
+/// in hourly buckets
+pub struct UsageMeter {
+    repository: Arc<dyn ModuleRepository>,
+    api_calls: Mutex<HashMap<Uuid, u64>>,
+}
+
+impl UsageMeter {
+    pub fn new(repository: Arc<dyn ModuleRepository>) -> Self {
+        Self {
+            repository,
+            api_calls: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// Count a host API call; counts are stored with the next sample
+    pub fn record_api_call(&self, instance_id: Uuid) {
+        if let Ok(mut api_calls) = self.api_calls.lock() {
+            *api_calls.entry(instance_id).or_insert(0) += 1;
+        }
+    }
+
+    /// Add a resource usage sample covering the last `seconds` to the
+    /// instance's current hour
+    pub async fn record_sample(
+        &self,
+        instance_id: Uuid,
+        module_id: &str,
+        tenant_id: &str,
+        usage: &ResourceUsage,
+        seconds: f64,
+    ) -> ModuleResult<()> {
+        let api_calls = self.api_calls.lock()
+            .map(|mut api_calls| api_calls.remove(&instance_id).unwrap_or(0))
+            .unwrap_or(0);
+
+        self.repository.add_module_usage(&ModuleUsage {
+            instance_id,
+            module_id: module_id.to_string(),
+            tenant_id: tenant_id.to_string(),
+            period_start: hour_start(usage.last_measured),
+            cpu_seconds: usage.cpu_percent as f64 / 100.0 * seconds,
+            memory_mb_seconds: usage.memory_mb as f64 * seconds,
+            storage_mb_seconds: usage.disk_mb as f64 * seconds,
+            api_calls,
+        }).await
+    }
+
+    /// A tenant's usage between two times, per module instance and in total
+    pub async fn tenant_usage(&self, tenant_id: &str, from: DateTime<Utc>, to: DateTime<Utc>) -> ModuleResult<TenantUsageReport> {
+        if from >= to {
+            return Err(ModuleError::ValidationFailed("Usage period must end after it starts".to_string()));
+        }
+
+        let mut modules: Vec<ModuleUsageTotals> = Vec::new();
+        let mut totals = UsageTotals::default();
+        for usage in self.repository.list_tenant_module_usage(tenant_id, hour_start(from), to).await? {
+            totals.add(&usage);
+            match modules.iter_mut().find(|module| module.instance_id == usage.instance_id) {
+                Some(module) => module.usage.add(&usage),
+                None => {
+                    let mut module = ModuleUsageTotals {
+                        module_id: usage.module_id.clone(),
+                        instance_id: usage.instance_id,
+                        usage: UsageTotals::default(),
+                    };
+                    module.usage.add(&usage);
+                    modules.push(module);
+                }
+            }
+        }
+
+        Ok(TenantUsageReport {
+            tenant_id: tenant_id.to_string(),
+            from,
+            to,
+            modules,
+            totals,
+        })
+    }
+}
+
+/// Reports the usage of usage-priced marketplace modules to license-service,
+/// which bills tenants and shares the revenue with the module's developer
+pub struct BillingFeed {
+    config: MeteringConfig,
+    repository: Arc<dyn ModuleRepository>,
+    marketplace: Arc<ModuleMarketplace>,
+    client: reqwest::Client,
+}
+
+impl BillingFeed {
+    pub fn new(config: MeteringConfig, repository: Arc<dyn ModuleRepository>, marketplace: Arc<ModuleMarketplace>) -> Self {
+        Self {
+            config,
+            repository,
+            marketplace,
+            client: reqwest::Client::new(),
+        }
+    }
+
+    /// Report every hour of usage that has ended and was not reported yet.
+    /// Hours of modules that are not usage-priced are marked reported
+    /// without a charge.
+    pub async fn report_closed_hours(&self) -> ModuleResult<usize> {
+        let usage = self.repository.list_unreported_module_usage(hour_start(Utc::now())).await?;
+        let mut pricing: HashMap<(String, String), ModulePricing> = HashMap::new();
+        let mut reported = 0;
+
+        for hour in usage {
+            let key = (hour.module_id.clone(), hour.tenant_id.clone());
+            if !pricing.contains_key(&key) {
+                let module_pricing = self.marketplace.get_module_pricing(&hour.module_id, &hour.tenant_id).await?;
+                pricing.insert(key.clone(), module_pricing);
+            }
+
+            if let Some(request) = charge_request(&hour, &pricing[&key]) {
+                self.post_charge(&request).await?;
+                reported += 1;
+            }
+            self.repository.mark_module_usage_reported(hour.instance_id, hour.period_start).await?;
+        }
+
+        if reported > 0 {
+            info!("Reported {} hours of module usage to license-service", reported);
+        }
+        Ok(reported)
+    }
+
+    async fn post_charge(&self, request: &ModuleUsageChargeRequest) -> ModuleResult<()> {
+        let response = self.client
+            .post(format!("{}/billing/module-usage", self.config.license_service_url))
+            .json(request)
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(ModuleError::NetworkError(
+                format!("License service returned {} for usage of {}", response.status(), request.module_id)
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The charge for an hour of usage, if the module is billed by usage
+fn charge_request(usage: &ModuleUsage, pricing: &ModulePricing) -> Option<ModuleUsageChargeRequest> {
+    if !matches!(pricing.pricing_model, PricingModel::Usage) {
+        return None;
+    }
+    let (Some(rates), Some(developer_id)) = (&pricing.usage_rates, &pricing.developer_id) else {
+        warn!("Usage-priced module {} has no usage rates or developer", usage.module_id);
+        return None;
+    };
+    // License-service identifies tenants by UUID
+    let Ok(tenant_id) = Uuid::parse_str(&usage.tenant_id) else {
+        warn!("Not billing usage of {} by tenant {}: not a UUID", usage.module_id, usage.tenant_id);
+        return None;
+    };
+
+    let lines = [
+        ("cpu_seconds", usage.cpu_seconds, rates.cpu_second),
+        ("memory_gb_hours", usage.memory_mb_seconds / MB_SECONDS_PER_GB_HOUR, rates.memory_gb_hour),
+        ("storage_gb_hours", usage.storage_mb_seconds / MB_SECONDS_PER_GB_HOUR, rates.storage_gb_hour),
+        ("api_calls", usage.api_calls as f64, rates.api_call),
+    ]
+    .into_iter()
+    .filter(|(_, quantity, unit_price)| *quantity > 0.0 && *unit_price > 0.0)
+    .map(|(metric, quantity, unit_price)| ModuleUsageChargeLine {
+        metric: metric.to_string(),
+        quantity,
+        unit_price,
+    })
+    .collect::<Vec<_>>();
+    if lines.is_empty() {
+        return None;
+    }
+
+    Some(ModuleUsageChargeRequest {
+        tenant_id,
+        module_id: usage.module_id.clone(),
+        instance_id: usage.instance_id,
+        developer_id: developer_id.clone(),
+        period_start: usage.period_start,
+        period_end: usage.period_start + chrono::Duration::hours(1),
+        currency: pricing.currency.clone(),
+        lines,
+    })
+}
+
+fn hour_start(time: DateTime<Utc>) -> DateTime<Utc> {
+    time.duration_trunc(chrono::Duration::hours(1)).unwrap_or(time)
+}
@@ -4,6 +4,10 @@ use uuid::Uuid;
 use std::collections::HashMap;
 use semver::Version;
 
+use crate::permissions::{CapabilityConsent, CapabilityManifest};
+use crate::signing::PackageSignature;
+use crate::events::EventDeclaration;
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ModuleMetadata {
     pub id: String,
@@ -59,9 +63,22 @@ pub struct ModuleManifest {
     pub dependencies: Vec<ModuleDependency>,
     pub capabilities: ModuleCapabilities,
     pub permissions: Vec<ModulePermission>,
+    /// Host capabilities the module needs; granted per tenant on install
+    #[serde(default)]
+    pub required_capabilities: CapabilityManifest,
+    /// Events the module publishes on the event bus, in its own namespace
+    #[serde(default)]
+    pub published_events: Vec<EventDeclaration>,
     pub resources: ResourceRequirements,
     pub configuration: ModuleConfiguration,
     pub extension_points: ExtensionPoints,
+    /// Micro-frontends the frontend entry exposes for the shell to mount
+    #[serde(default)]
+    pub micro_frontends: Vec<MicroFrontend>,
+    /// Commands run in the module's sandbox as it is installed, upgraded
+    /// and uninstalled, e.g. to migrate its schema
+    #[serde(default)]
+    pub lifecycle_hooks: LifecycleHooks,
     pub sandbox_config: SandboxConfiguration,
 }
 
@@ -205,6 +222,44 @@ pub struct ExtensionPoints {
     pub test_entry: Option<String>,
 }
 
+/// A micro-frontend exposed by a module's frontend entry (a module
+/// federation remote entry)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MicroFrontend {
+    /// Unique within the module
+    pub name: String,
+    /// Module the remote entry exposes, e.g. "./Dashboard"
+    pub exposed_module: String,
+    /// Shell slot it is mounted in, e.g. "dashboard", "navigation" or "page"
+    pub slot: String,
+    /// Shell route it is mounted under, for pages
+    #[serde(default)]
+    pub route: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct LifecycleHooks {
+    #[serde(default)]
+    pub install: Option<LifecycleHook>,
+    #[serde(default)]
+    pub upgrade: Option<LifecycleHook>,
+    #[serde(default)]
+    pub uninstall: Option<LifecycleHook>,
+}
+
+/// A lifecycle hook. Both commands get the phase and a JSON context as
+/// arguments.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LifecycleHook {
+    pub run: String,
+    /// Undoes `run` when it or a later step of the operation fails
+    #[serde(default)]
+    pub compensate: Option<String>,
+    /// Defaults to the service's hook timeout
+    #[serde(default)]
+    pub timeout_seconds: Option<u64>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SandboxConfiguration {
     pub isolation_level: IsolationLevel,
@@ -308,8 +363,32 @@ pub struct ModulePackage {
     pub manifest: ModuleManifest,
     pub content: Vec<u8>,
     pub checksum: String,
-    pub signature: Option<String>,
+    pub signature: Option<PackageSignature>,
     pub size_bytes: u64,
+    /// Frontend assets served to the shell, from the directory of the
+    /// frontend entry
+    #[serde(default)]
+    pub assets: Vec<PackageAsset>,
+    /// Third-party libraries the module was built with, from its lockfiles;
+    /// looked up for known vulnerabilities when the package is scanned
+    #[serde(default)]
+    pub locked_dependencies: Vec<LockedDependency>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageAsset {
+    /// Path relative to the frontend entry's directory
+    pub path: String,
+    pub content_type: String,
+    pub content: Vec<u8>,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Serialize, Deserialize)]
+pub struct LockedDependency {
+    /// OSV ecosystem of the library, e.g. crates.io or npm
+    pub ecosystem: String,
+    pub name: String,
+    pub version: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -369,6 +448,9 @@ pub struct InstallModuleRequest {
     pub user_id: String,
     pub configuration: Option<serde_json::Value>,
     pub auto_activate: bool,
+    /// Required when the module declares capabilities
+    #[serde(default)]
+    pub consent: Option<CapabilityConsent>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -386,6 +468,9 @@ pub struct UpdateModuleRequest {
     pub target_version: Option<Version>,
     pub preserve_config: bool,
     pub backup_current: bool,
+    /// Required when the new version declares capabilities not granted yet
+    #[serde(default)]
+    pub consent: Option<CapabilityConsent>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -0,0 +1,241 @@
+use serde::{Deserialize, Serialize};
+use chrono::{DateTime, Utc};
+use semver::Version;
+
+use crate::{ModuleResult, ModuleError};
+use crate::events::event_type_matches;
+
+/// Capabilities a module needs from the host, declared in its manifest.
+/// A module gets nothing it has not declared and a tenant admin has not
+/// consented to.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct CapabilityManifest {
+    /// Platform scopes as `resource:action`, e.g. `storage:write`
+    pub scopes: Vec<String>,
+    /// API endpoints as `METHOD /path`, matched against the path of the URLs
+    /// the module requests. `*` matches one path segment, a trailing `**` the
+    /// rest of the path, e.g. `GET /api/v1/users/*`
+    pub api_endpoints: Vec<String>,
+    /// Event types the module subscribes to; a trailing `*` matches any
+    /// suffix, e.g. `user.*`
+    pub event_subscriptions: Vec<String>,
+}
+
+impl CapabilityManifest {
+    pub fn is_empty(&self) -> bool {
+        self.scopes.is_empty() && self.api_endpoints.is_empty() && self.event_subscriptions.is_empty()
+    }
+
+    /// Reject malformed declarations, so a module cannot be installed with
+    /// capabilities no check would ever match
+    pub fn validate(&self) -> ModuleResult<()> {
+        for scope in &self.scopes {
+            match scope.split_once(':') {
+                Some((resource, action)) if !resource.is_empty() && !action.is_empty() => {}
+                _ => return Err(ModuleError::ValidationFailed(
+                    format!("Scope {} is not of the form resource:action", scope)
+                )),
+            }
+        }
+
+        for endpoint in &self.api_endpoints {
+            if parse_endpoint(endpoint).is_none() {
+                return Err(ModuleError::ValidationFailed(
+                    format!("API endpoint {} is not of the form METHOD /path", endpoint)
+                ));
+            }
+        }
+
+        for event_type in &self.event_subscriptions {
+            if event_type.is_empty() || event_type.strip_suffix('*').unwrap_or(event_type).contains('*') {
+                return Err(ModuleError::ValidationFailed(
+                    format!("Event subscription {} may only end in a wildcard", event_type)
+                ));
+            }
+        }
+
+        Ok(())
+    }
+
+    /// The capabilities declared here that `granted` does not contain
+    pub fn missing_from(&self, granted: &CapabilityManifest) -> CapabilityManifest {
+        fn missing(declared: &[String], granted: &[String]) -> Vec<String> {
+            declared.iter().filter(|entry| !granted.contains(entry)).cloned().collect()
+        }
+
+        CapabilityManifest {
+            scopes: missing(&self.scopes, &granted.scopes),
+            api_endpoints: missing(&self.api_endpoints, &granted.api_endpoints),
+            event_subscriptions: missing(&self.event_subscriptions, &granted.event_subscriptions),
+        }
+    }
+
+    /// One line per capability, as a tenant admin is asked to consent to it
+    pub fn describe(&self) -> Vec<CapabilityDescription> {
+        let scopes = self.scopes.iter().map(|scope| {
+            let (resource, action) = scope.split_once(':').unwrap_or((scope, ""));
+            CapabilityDescription {
+                kind: CapabilityKind::Scope,
+                capability: scope.clone(),
+                description: format!("{} {} of your organization", describe_action(action), resource),
+            }
+        });
+
+        let endpoints = self.api_endpoints.iter().map(|endpoint| CapabilityDescription {
+            kind: CapabilityKind::ApiEndpoint,
+            capability: endpoint.clone(),
+            description: format!("Call {}", endpoint),
+        });
+
+        let subscriptions = self.event_subscriptions.iter().map(|event_type| CapabilityDescription {
+            kind: CapabilityKind::EventSubscription,
+            capability: event_type.clone(),
+            description: format!("Receive {} events", event_type),
+        });
+
+        scopes.chain(endpoints).chain(subscriptions).collect()
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum CapabilityKind {
+    Scope,
+    ApiEndpoint,
+    EventSubscription,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapabilityDescription {
+    pub kind: CapabilityKind,
+    pub capability: String,
+    pub description: String,
+}
+
+/// What a tenant admin is shown before installing or updating a module
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConsentScreen {
+    pub module_id: String,
+    pub module_name: String,
+    pub version: Version,
+    pub author: String,
+    /// Everything the module version needs
+    pub capabilities: CapabilityManifest,
+    pub descriptions: Vec<CapabilityDescription>,
+    /// What the tenant has not granted the module yet; all of it when the
+    /// module is not installed
+    pub newly_requested: CapabilityManifest,
+}
+
+/// A tenant admin's consent to a module's capabilities, sent with the
+/// install or update request
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapabilityConsent {
+    pub granted_by: String,
+    pub capabilities: CapabilityManifest,
+}
+
+/// The capabilities a tenant granted a module
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CapabilityGrant {
+    pub module_id: String,
+    pub tenant_id: String,
+    pub version: Version,
+    pub capabilities: CapabilityManifest,
+    pub granted_by: String,
+    pub granted_at: DateTime<Utc>,
+}
+
+/// Runtime check of a module instance's host API calls against its grant.
+/// A module not bound to a grant can do nothing that needs one.
+#[derive(Debug, Clone, Default)]
+pub struct CapabilityGrants {
+    module_id: String,
+    capabilities: CapabilityManifest,
+}
+
+impl CapabilityGrants {
+    pub fn new(module_id: &str, capabilities: CapabilityManifest) -> Self {
+        Self {
+            module_id: module_id.to_string(),
+            capabilities,
+        }
+    }
+
+    pub fn capabilities(&self) -> &CapabilityManifest {
+        &self.capabilities
+    }
+
+    pub fn check_scope(&self, scope: &str) -> ModuleResult<()> {
+        if self.capabilities.scopes.iter().any(|granted| granted == scope) {
+            return Ok(());
+        }
+        Err(self.denied(&format!("scope {}", scope)))
+    }
+
+    pub fn check_endpoint(&self, method: &str, path: &str) -> ModuleResult<()> {
+        let granted = self.capabilities.api_endpoints.iter()
+            .filter_map(|endpoint| parse_endpoint(endpoint))
+            .any(|(granted_method, pattern)| {
+                (granted_method == "*" || granted_method.eq_ignore_ascii_case(method))
+                    && path_matches(pattern, path)
+            });
+        if granted {
+            return Ok(());
+        }
+        Err(self.denied(&format!("API endpoint {} {}", method, path)))
+    }
+
+    pub fn check_subscription(&self, event_type: &str) -> ModuleResult<()> {
+        let granted = self.capabilities.event_subscriptions.iter()
+            .any(|granted| event_type_matches(granted, event_type));
+        if granted {
+            return Ok(());
+        }
+        Err(self.denied(&format!("subscription to {} events", event_type)))
+    }
+
+    fn denied(&self, capability: &str) -> ModuleError {
+        ModuleError::PermissionDenied(
+            format!("Module {} has not been granted {}", self.module_id, capability)
+        )
+    }
+}
+
+fn parse_endpoint(endpoint: &str) -> Option<(&str, &str)> {
+    let (method, path) = endpoint.trim().split_once(' ')?;
+    let path = path.trim();
+    if method.is_empty() || !path.starts_with('/') {
+        return None;
+    }
+    Some((method, path))
+}
+
+fn path_matches(pattern: &str, path: &str) -> bool {
+    let mut pattern_segments = pattern.trim_end_matches('/').split('/');
+    let mut path_segments = path.trim_end_matches('/').split('/');
+
+    loop {
+        match (pattern_segments.next(), path_segments.next()) {
+            (Some("**"), _) => return true,
+            (Some(expected), Some(actual)) if expected == "*" || expected == actual => {}
+            (None, None) => return true,
+            _ => return false,
+        }
+    }
+}
+
+fn describe_action(action: &str) -> String {
+    match action {
+        "read" => "Read".to_string(),
+        "write" => "Change".to_string(),
+        "delete" => "Delete".to_string(),
+        other => {
+            let mut chars = other.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => "Access".to_string(),
+            }
+        }
+    }
+}
@@ -7,7 +7,15 @@ use uuid::Uuid;
 use crate::{
     ModuleResult, ModuleError, ModuleRepository as ModuleRepositoryTrait,
     ModuleMetadata, ModuleInstance, ModuleSearchQuery, ModuleSearchResult,
-    ModuleStatus, SortBy,
+    ModuleStatus, SortBy, CapabilityGrant, DeveloperKey, Revocation, RevocationKind,
+    rollout::{ModuleRollout, RolloutStatus},
+    events::{BusEvent, DeliveryStatus, EventDelivery},
+    settings::ModuleSettings,
+    metering::ModuleUsage,
+    assets::{FrontendBundle, ModuleAsset},
+    search::{category_key, pricing_key, IndexHit, MarketplaceListing},
+    security::{ReviewStatus, SecurityReport},
+    jobs::{JobRun, JobRunStatus, JobState, ModuleJob},
 };
 
 /// PostgreSQL-based module repository implementation
@@ -20,6 +28,36 @@ impl PostgresModuleRepository {
         Self { pool }
     }
 
+    async fn get_rollouts(&self, ids: Vec<Uuid>) -> ModuleResult<Vec<ModuleRollout>> {
+        let mut rollouts = Vec::with_capacity(ids.len());
+        for id in ids {
+            if let Some(rollout) = self.get_rollout(id).await? {
+                rollouts.push(rollout);
+            }
+        }
+        Ok(rollouts)
+    }
+
+    fn event_delivery(
+        id: Uuid,
+        instance_id: Uuid,
+        event: serde_json::Value,
+        status: &str,
+        attempts: i32,
+        next_attempt_at: chrono::DateTime<chrono::Utc>,
+        last_error: Option<String>,
+    ) -> ModuleResult<EventDelivery> {
+        Ok(EventDelivery {
+            id,
+            instance_id,
+            event: serde_json::from_value::<BusEvent>(event)?,
+            status: DeliveryStatus::parse(status)?,
+            attempts: attempts as u32,
+            next_attempt_at,
+            last_error,
+        })
+    }
+
     /// Initialize database tables for module storage
     pub async fn initialize(&self) -> ModuleResult<()> {
         // Create modules table
@@ -84,6 +122,272 @@ impl PostgresModuleRepository {
         .execute(&self.pool)
         .await?;
 
+        // Create capability grants table
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS module_capability_grants (
+                module_id VARCHAR NOT NULL,
+                tenant_id VARCHAR NOT NULL,
+                version VARCHAR NOT NULL,
+                capabilities JSONB NOT NULL DEFAULT '{}',
+                granted_by VARCHAR NOT NULL,
+                granted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (module_id, tenant_id),
+                FOREIGN KEY (module_id) REFERENCES modules(id) ON DELETE CASCADE
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Create developer signing keys table
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS module_developer_keys (
+                key_id VARCHAR PRIMARY KEY,
+                developer_id VARCHAR NOT NULL,
+                public_key VARCHAR NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Create signing revocation list table
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS module_revocations (
+                kind VARCHAR NOT NULL,
+                value VARCHAR NOT NULL,
+                reason TEXT,
+                revoked_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (kind, value)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Create module rollouts table
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS module_rollouts (
+                id UUID PRIMARY KEY,
+                module_id VARCHAR NOT NULL,
+                target_version VARCHAR NOT NULL,
+                policy JSONB NOT NULL,
+                status VARCHAR NOT NULL,
+                next_stage INTEGER NOT NULL DEFAULT 0,
+                instances JSONB NOT NULL DEFAULT '[]',
+                reason TEXT,
+                initiated_by VARCHAR NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                FOREIGN KEY (module_id) REFERENCES modules(id) ON DELETE CASCADE
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query!(
+            "CREATE INDEX IF NOT EXISTS idx_module_rollouts_module ON module_rollouts(module_id, created_at DESC)"
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Create module event deliveries table
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS module_event_deliveries (
+                id UUID PRIMARY KEY,
+                instance_id UUID NOT NULL,
+                tenant_id VARCHAR NOT NULL,
+                event JSONB NOT NULL,
+                status VARCHAR NOT NULL DEFAULT 'pending',
+                attempts INTEGER NOT NULL DEFAULT 0,
+                next_attempt_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                last_error TEXT,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                FOREIGN KEY (instance_id) REFERENCES module_instances(id) ON DELETE CASCADE
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query!(
+            "CREATE INDEX IF NOT EXISTS idx_module_event_deliveries_due ON module_event_deliveries(status, next_attempt_at)"
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Create hourly module usage table
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS module_usage_hourly (
+                instance_id UUID NOT NULL,
+                period_start TIMESTAMPTZ NOT NULL,
+                module_id VARCHAR NOT NULL,
+                tenant_id VARCHAR NOT NULL,
+                cpu_seconds DOUBLE PRECISION NOT NULL DEFAULT 0,
+                memory_mb_seconds DOUBLE PRECISION NOT NULL DEFAULT 0,
+                storage_mb_seconds DOUBLE PRECISION NOT NULL DEFAULT 0,
+                api_calls BIGINT NOT NULL DEFAULT 0,
+                reported_at TIMESTAMPTZ,
+                PRIMARY KEY (instance_id, period_start)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query!(
+            "CREATE INDEX IF NOT EXISTS idx_module_usage_hourly_tenant ON module_usage_hourly(tenant_id, period_start)"
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Create module settings versions table
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS module_settings_versions (
+                instance_id UUID NOT NULL,
+                version INTEGER NOT NULL,
+                settings JSONB NOT NULL,
+                updated_by VARCHAR NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (instance_id, version),
+                FOREIGN KEY (instance_id) REFERENCES module_instances(id) ON DELETE CASCADE
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        // Create module frontend asset tables
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS module_assets (
+                module_id VARCHAR NOT NULL,
+                version VARCHAR NOT NULL,
+                path VARCHAR NOT NULL,
+                content_type VARCHAR NOT NULL,
+                etag VARCHAR NOT NULL,
+                size_bytes BIGINT NOT NULL,
+                content BYTEA NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (module_id, version, path)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS module_frontend_bundles (
+                module_id VARCHAR NOT NULL,
+                version VARCHAR NOT NULL,
+                remote_entry VARCHAR NOT NULL,
+                integrity VARCHAR NOT NULL,
+                micro_frontends JSONB NOT NULL,
+                created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+                PRIMARY KEY (module_id, version)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS marketplace_listings (
+                module_id VARCHAR PRIMARY KEY,
+                metadata JSONB NOT NULL,
+                categories TEXT[] NOT NULL,
+                pricing_model VARCHAR NOT NULL,
+                price DOUBLE PRECISION NOT NULL,
+                currency VARCHAR NOT NULL,
+                rating_average DOUBLE PRECISION NOT NULL,
+                review_count INTEGER NOT NULL,
+                install_count BIGINT NOT NULL,
+                search_vector TSVECTOR NOT NULL,
+                indexed_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS module_security_reports (
+                scan_id UUID PRIMARY KEY,
+                module_id VARCHAR NOT NULL,
+                version VARCHAR NOT NULL,
+                checksum VARCHAR NOT NULL,
+                score SMALLINT NOT NULL,
+                issues JSONB NOT NULL,
+                stages JSONB NOT NULL,
+                review_status VARCHAR NOT NULL,
+                review_reasons JSONB NOT NULL,
+                reviewer VARCHAR,
+                review_notes TEXT,
+                reviewed_at TIMESTAMPTZ,
+                scanned_at TIMESTAMPTZ NOT NULL
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS module_jobs (
+                id UUID PRIMARY KEY,
+                instance_id UUID NOT NULL,
+                tenant_id VARCHAR NOT NULL,
+                module_id VARCHAR NOT NULL,
+                name VARCHAR NOT NULL,
+                schedule JSONB NOT NULL,
+                payload JSONB NOT NULL,
+                timeout_seconds BIGINT NOT NULL,
+                schedule_id VARCHAR,
+                state VARCHAR NOT NULL,
+                disabled_reason TEXT,
+                disabled_by VARCHAR,
+                consecutive_failures INTEGER NOT NULL DEFAULT 0,
+                last_run_at TIMESTAMPTZ,
+                last_run_status VARCHAR,
+                last_error TEXT,
+                created_at TIMESTAMPTZ NOT NULL,
+                updated_at TIMESTAMPTZ NOT NULL,
+                UNIQUE (instance_id, name)
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query!(
+            r#"
+            CREATE TABLE IF NOT EXISTS module_job_runs (
+                id UUID PRIMARY KEY,
+                job_id UUID NOT NULL REFERENCES module_jobs(id) ON DELETE CASCADE,
+                instance_id UUID NOT NULL,
+                tenant_id VARCHAR NOT NULL,
+                status VARCHAR NOT NULL,
+                error TEXT,
+                started_at TIMESTAMPTZ NOT NULL,
+                finished_at TIMESTAMPTZ
+            )
+            "#
+        )
+        .execute(&self.pool)
+        .await?;
+
         // Create indexes
         sqlx::query!(
             "CREATE INDEX IF NOT EXISTS idx_modules_name ON modules(name)"
@@ -109,6 +413,42 @@ impl PostgresModuleRepository {
         .execute(&self.pool)
         .await?;
 
+        sqlx::query!(
+            "CREATE INDEX IF NOT EXISTS idx_marketplace_listings_search ON marketplace_listings USING GIN(search_vector)"
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query!(
+            "CREATE INDEX IF NOT EXISTS idx_module_security_reports_module ON module_security_reports(module_id, version)"
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query!(
+            "CREATE INDEX IF NOT EXISTS idx_module_security_reports_status ON module_security_reports(review_status, scanned_at)"
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query!(
+            "CREATE INDEX IF NOT EXISTS idx_module_jobs_tenant ON module_jobs(tenant_id)"
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query!(
+            "CREATE INDEX IF NOT EXISTS idx_module_job_runs_job ON module_job_runs(job_id, started_at DESC)"
+        )
+        .execute(&self.pool)
+        .await?;
+
+        sqlx::query!(
+            "CREATE INDEX IF NOT EXISTS idx_module_job_runs_running ON module_job_runs(instance_id) WHERE status = 'running'"
+        )
+        .execute(&self.pool)
+        .await?;
+
         Ok(())
     }
 }
@@ -477,6 +817,7 @@ impl ModuleRepositoryTrait for PostgresModuleRepository {
                 $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20, $21, $22
             )
             ON CONFLICT (id) DO UPDATE SET
+                version = EXCLUDED.version,
                 status = EXCLUDED.status,
                 configuration = EXCLUDED.configuration,
                 activated_at = EXCLUDED.activated_at,
@@ -669,26 +1010,1341 @@ impl ModuleRepositoryTrait for PostgresModuleRepository {
         Ok(instances)
     }
 
-    async fn update_instance_status(&self, instance_id: Uuid, status: ModuleStatus) -> ModuleResult<()> {
-        sqlx::query!(
-            "UPDATE module_instances SET status = $1, last_updated = NOW() WHERE id = $2",
-            format!("{:?}", status),
-            instance_id
+    async fn list_module_instances(&self, module_id: &str) -> ModuleResult<Vec<ModuleInstance>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT 
+                id, module_id, tenant_id, version, status, configuration,
+                installation_path, installed_at, activated_at, last_updated,
+                memory_mb, cpu_percent, disk_mb, network_in_mbps, network_out_mbps,
+                active_connections, is_healthy, last_health_check, error_count,
+                warning_count, uptime_seconds, response_time_ms
+            FROM module_instances 
+            WHERE module_id = $1
+            ORDER BY installed_at ASC
+            "#,
+            module_id
         )
-        .execute(&self.pool)
+        .fetch_all(&self.pool)
         .await?;
 
-        Ok(())
-    }
+        let mut instances = Vec::new();
+        for row in rows {
+            let version = semver::Version::parse(&row.version)
+                .map_err(|e| ModuleError::SerializationError(e.to_string()))?;
 
-    async fn delete_instance(&self, instance_id: Uuid) -> ModuleResult<()> {
-        sqlx::query!(
-            "DELETE FROM module_instances WHERE id = $1",
-            instance_id
-        )
-        .execute(&self.pool)
-        .await?;
+            let status = match row.status.as_str() {
+                "Downloaded" => ModuleStatus::Downloaded,
+                "Installing" => ModuleStatus::Installing,
+                "Installed" => ModuleStatus::Installed,
+                "Activating" => ModuleStatus::Activating,
+                "Active" => ModuleStatus::Active,
+                "Deactivating" => ModuleStatus::Deactivating,
+                "Inactive" => ModuleStatus::Inactive,
+                "Updating" => ModuleStatus::Updating,
+                "Uninstalling" => ModuleStatus::Uninstalling,
+                "Failed" => ModuleStatus::Failed,
+                "Suspended" => ModuleStatus::Suspended,
+                _ => ModuleStatus::Failed,
+            };
 
-        Ok(())
-    }
-}
\ No newline at end of file
+            let instance = ModuleInstance {
+                id: row.id,
+                module_id: row.module_id,
+                tenant_id: row.tenant_id,
+                version,
+                status,
+                configuration: row.configuration,
+                installation_path: row.installation_path,
+                installed_at: row.installed_at,
+                activated_at: row.activated_at,
+                last_updated: row.last_updated,
+                resource_usage: crate::ResourceUsage {
+                    memory_mb: row.memory_mb as u64,
+                    cpu_percent: row.cpu_percent,
+                    disk_mb: row.disk_mb as u64,
+                    network_in_mbps: row.network_in_mbps,
+                    network_out_mbps: row.network_out_mbps,
+                    active_connections: row.active_connections as u32,
+                    last_measured: chrono::Utc::now(),
+                },
+                health_status: crate::HealthStatus {
+                    is_healthy: row.is_healthy,
+                    last_health_check: row.last_health_check,
+                    error_count: row.error_count as u32,
+                    warning_count: row.warning_count as u32,
+                    uptime_seconds: row.uptime_seconds as u64,
+                    response_time_ms: row.response_time_ms as u64,
+                },
+            };
+
+            instances.push(instance);
+        }
+
+        Ok(instances)
+    }
+
+    async fn update_instance_status(&self, instance_id: Uuid, status: ModuleStatus) -> ModuleResult<()> {
+        sqlx::query!(
+            "UPDATE module_instances SET status = $1, last_updated = NOW() WHERE id = $2",
+            format!("{:?}", status),
+            instance_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn delete_instance(&self, instance_id: Uuid) -> ModuleResult<()> {
+        sqlx::query!(
+            "DELETE FROM module_instances WHERE id = $1",
+            instance_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn save_capability_grant(&self, grant: &CapabilityGrant) -> ModuleResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO module_capability_grants (
+                module_id, tenant_id, version, capabilities, granted_by, granted_at
+            ) VALUES (
+                $1, $2, $3, $4, $5, $6
+            )
+            ON CONFLICT (module_id, tenant_id) DO UPDATE SET
+                version = EXCLUDED.version,
+                capabilities = EXCLUDED.capabilities,
+                granted_by = EXCLUDED.granted_by,
+                granted_at = EXCLUDED.granted_at
+            "#,
+            grant.module_id,
+            grant.tenant_id,
+            grant.version.to_string(),
+            serde_json::to_value(&grant.capabilities)?,
+            grant.granted_by,
+            grant.granted_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_capability_grant(&self, module_id: &str, tenant_id: &str) -> ModuleResult<Option<CapabilityGrant>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT module_id, tenant_id, version, capabilities, granted_by, granted_at
+            FROM module_capability_grants
+            WHERE module_id = $1 AND tenant_id = $2
+            "#,
+            module_id,
+            tenant_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        if let Some(row) = row {
+            let version = semver::Version::parse(&row.version)
+                .map_err(|e| ModuleError::SerializationError(e.to_string()))?;
+
+            Ok(Some(CapabilityGrant {
+                module_id: row.module_id,
+                tenant_id: row.tenant_id,
+                version,
+                capabilities: serde_json::from_value(row.capabilities)?,
+                granted_by: row.granted_by,
+                granted_at: row.granted_at,
+            }))
+        } else {
+            Ok(None)
+        }
+    }
+
+    async fn delete_capability_grant(&self, module_id: &str, tenant_id: &str) -> ModuleResult<()> {
+        sqlx::query!(
+            "DELETE FROM module_capability_grants WHERE module_id = $1 AND tenant_id = $2",
+            module_id,
+            tenant_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn save_developer_key(&self, key: &DeveloperKey) -> ModuleResult<()> {
+        // A key id is bound to its public key for good; signatures made with
+        // it must not start verifying against another key
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO module_developer_keys (key_id, developer_id, public_key, created_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (key_id) DO NOTHING
+            "#,
+            key.key_id,
+            key.developer_id,
+            key.public_key,
+            key.created_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(ModuleError::AlreadyExists(format!("Developer key {}", key.key_id)));
+        }
+
+        Ok(())
+    }
+
+    async fn get_developer_key(&self, key_id: &str) -> ModuleResult<Option<DeveloperKey>> {
+        let row = sqlx::query!(
+            "SELECT key_id, developer_id, public_key, created_at FROM module_developer_keys WHERE key_id = $1",
+            key_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| DeveloperKey {
+            key_id: row.key_id,
+            developer_id: row.developer_id,
+            public_key: row.public_key,
+            created_at: row.created_at,
+        }))
+    }
+
+    async fn save_revocation(&self, revocation: &Revocation) -> ModuleResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO module_revocations (kind, value, reason, revoked_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (kind, value) DO NOTHING
+            "#,
+            revocation.kind.as_str(),
+            revocation.value,
+            revocation.reason,
+            revocation.revoked_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list_revocations(&self) -> ModuleResult<Vec<Revocation>> {
+        let rows = sqlx::query!(
+            "SELECT kind, value, reason, revoked_at FROM module_revocations ORDER BY revoked_at DESC"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| Ok(Revocation {
+                kind: RevocationKind::parse(&row.kind)?,
+                value: row.value,
+                reason: row.reason,
+                revoked_at: row.revoked_at,
+            }))
+            .collect()
+    }
+
+    async fn is_revoked(&self, kind: RevocationKind, value: &str) -> ModuleResult<bool> {
+        let row = sqlx::query!(
+            "SELECT EXISTS(SELECT 1 FROM module_revocations WHERE kind = $1 AND value = $2) AS \"revoked!\"",
+            kind.as_str(),
+            value
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(row.revoked)
+    }
+
+    async fn create_rollout(&self, rollout: &ModuleRollout) -> ModuleResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO module_rollouts (
+                id, module_id, target_version, policy, status, next_stage,
+                instances, reason, initiated_by, created_at, updated_at
+            ) VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11
+            )
+            "#,
+            rollout.id,
+            rollout.module_id,
+            rollout.target_version.to_string(),
+            serde_json::to_value(&rollout.policy)?,
+            rollout.status.as_str(),
+            rollout.next_stage as i32,
+            serde_json::to_value(&rollout.instances)?,
+            rollout.reason,
+            rollout.initiated_by,
+            rollout.created_at,
+            rollout.updated_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_rollout(&self, rollout_id: Uuid) -> ModuleResult<Option<ModuleRollout>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id, module_id, target_version, policy, status, next_stage,
+                   instances, reason, initiated_by, created_at, updated_at
+            FROM module_rollouts
+            WHERE id = $1
+            "#,
+            rollout_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(Some(ModuleRollout {
+                id: row.id,
+                module_id: row.module_id,
+                target_version: semver::Version::parse(&row.target_version)
+                    .map_err(|e| ModuleError::SerializationError(e.to_string()))?,
+                policy: serde_json::from_value(row.policy)?,
+                status: RolloutStatus::parse(&row.status)?,
+                next_stage: row.next_stage as usize,
+                instances: serde_json::from_value(row.instances)?,
+                reason: row.reason,
+                initiated_by: row.initiated_by,
+                created_at: row.created_at,
+                updated_at: row.updated_at,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    async fn list_module_rollouts(&self, module_id: &str) -> ModuleResult<Vec<ModuleRollout>> {
+        let ids = sqlx::query_scalar!(
+            "SELECT id FROM module_rollouts WHERE module_id = $1 ORDER BY created_at DESC",
+            module_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        self.get_rollouts(ids).await
+    }
+
+    async fn list_rollouts_in_progress(&self) -> ModuleResult<Vec<ModuleRollout>> {
+        let ids = sqlx::query_scalar!(
+            "SELECT id FROM module_rollouts WHERE status IN ('running', 'rolling_back') ORDER BY created_at"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        self.get_rollouts(ids).await
+    }
+
+    async fn save_rollout_progress(&self, rollout: &ModuleRollout) -> ModuleResult<()> {
+        sqlx::query!(
+            "UPDATE module_rollouts SET next_stage = $1, instances = $2, updated_at = $3 WHERE id = $4",
+            rollout.next_stage as i32,
+            serde_json::to_value(&rollout.instances)?,
+            rollout.updated_at,
+            rollout.id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn transition_rollout(
+        &self,
+        rollout_id: Uuid,
+        from: RolloutStatus,
+        to: RolloutStatus,
+        reason: Option<&str>,
+    ) -> ModuleResult<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE module_rollouts
+            SET status = $1, reason = COALESCE($2, reason), updated_at = NOW()
+            WHERE id = $3 AND status = $4
+            "#,
+            to.as_str(),
+            reason,
+            rollout_id,
+            from.as_str()
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn save_event_deliveries(&self, deliveries: &[EventDelivery]) -> ModuleResult<()> {
+        let mut tx = self.pool.begin().await?;
+        for delivery in deliveries {
+            sqlx::query!(
+                r#"
+                INSERT INTO module_event_deliveries (
+                    id, instance_id, tenant_id, event, status, attempts, next_attempt_at, last_error
+                ) VALUES (
+                    $1, $2, $3, $4, $5, $6, $7, $8
+                )
+                "#,
+                delivery.id,
+                delivery.instance_id,
+                delivery.event.tenant_id,
+                serde_json::to_value(&delivery.event)?,
+                delivery.status.as_str(),
+                delivery.attempts as i32,
+                delivery.next_attempt_at,
+                delivery.last_error
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    async fn due_event_deliveries(&self, limit: u32) -> ModuleResult<Vec<EventDelivery>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, instance_id, event, status, attempts, next_attempt_at, last_error
+            FROM module_event_deliveries
+            WHERE status = 'pending' AND next_attempt_at <= NOW()
+            ORDER BY next_attempt_at
+            LIMIT $1
+            "#,
+            limit as i64
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| Self::event_delivery(
+                row.id, row.instance_id, row.event, &row.status, row.attempts, row.next_attempt_at, row.last_error,
+            ))
+            .collect()
+    }
+
+    async fn mark_event_delivered(&self, delivery_id: Uuid) -> ModuleResult<()> {
+        sqlx::query!("DELETE FROM module_event_deliveries WHERE id = $1", delivery_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn mark_event_delivery_failed(
+        &self,
+        delivery_id: Uuid,
+        attempts: u32,
+        error: &str,
+        next_attempt_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> ModuleResult<()> {
+        let status = match next_attempt_at {
+            Some(_) => DeliveryStatus::Pending,
+            None => DeliveryStatus::DeadLettered,
+        };
+
+        sqlx::query!(
+            r#"
+            UPDATE module_event_deliveries
+            SET status = $1, attempts = $2, last_error = $3, next_attempt_at = COALESCE($4, next_attempt_at)
+            WHERE id = $5
+            "#,
+            status.as_str(),
+            attempts as i32,
+            error,
+            next_attempt_at,
+            delivery_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list_dead_lettered_deliveries(&self, tenant_id: &str) -> ModuleResult<Vec<EventDelivery>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, instance_id, event, status, attempts, next_attempt_at, last_error
+            FROM module_event_deliveries
+            WHERE tenant_id = $1 AND status = 'dead_lettered'
+            ORDER BY created_at DESC
+            "#,
+            tenant_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| Self::event_delivery(
+                row.id, row.instance_id, row.event, &row.status, row.attempts, row.next_attempt_at, row.last_error,
+            ))
+            .collect()
+    }
+
+    async fn requeue_event_delivery(&self, tenant_id: &str, delivery_id: Uuid) -> ModuleResult<bool> {
+        let result = sqlx::query!(
+            r#"
+            UPDATE module_event_deliveries
+            SET status = 'pending', attempts = 0, next_attempt_at = NOW()
+            WHERE id = $1 AND tenant_id = $2 AND status = 'dead_lettered'
+            "#,
+            delivery_id,
+            tenant_id
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn save_settings_version(&self, settings: &ModuleSettings) -> ModuleResult<bool> {
+        let mut tx = self.pool.begin().await?;
+
+        let result = sqlx::query!(
+            r#"
+            INSERT INTO module_settings_versions (instance_id, version, settings, updated_by, created_at)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (instance_id, version) DO NOTHING
+            "#,
+            settings.instance_id,
+            settings.version as i32,
+            settings.values,
+            settings.updated_by,
+            settings.created_at
+        )
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Ok(false);
+        }
+
+        sqlx::query!(
+            "UPDATE module_instances SET configuration = $1, last_updated = $2 WHERE id = $3",
+            settings.values,
+            settings.created_at,
+            settings.instance_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn get_latest_settings(&self, instance_id: Uuid) -> ModuleResult<Option<ModuleSettings>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT instance_id, version, settings, updated_by, created_at
+            FROM module_settings_versions
+            WHERE instance_id = $1
+            ORDER BY version DESC
+            LIMIT 1
+            "#,
+            instance_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| ModuleSettings {
+            instance_id: row.instance_id,
+            version: row.version as u32,
+            values: row.settings,
+            updated_by: row.updated_by,
+            created_at: row.created_at,
+        }))
+    }
+
+    async fn list_settings_versions(&self, instance_id: Uuid) -> ModuleResult<Vec<ModuleSettings>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT instance_id, version, settings, updated_by, created_at
+            FROM module_settings_versions
+            WHERE instance_id = $1
+            ORDER BY version DESC
+            "#,
+            instance_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter()
+            .map(|row| ModuleSettings {
+                instance_id: row.instance_id,
+                version: row.version as u32,
+                values: row.settings,
+                updated_by: row.updated_by,
+                created_at: row.created_at,
+            })
+            .collect())
+    }
+
+    async fn add_module_usage(&self, usage: &ModuleUsage) -> ModuleResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO module_usage_hourly (
+                instance_id, period_start, module_id, tenant_id,
+                cpu_seconds, memory_mb_seconds, storage_mb_seconds, api_calls
+            ) VALUES (
+                $1, $2, $3, $4, $5, $6, $7, $8
+            )
+            ON CONFLICT (instance_id, period_start) DO UPDATE SET
+                cpu_seconds = module_usage_hourly.cpu_seconds + EXCLUDED.cpu_seconds,
+                memory_mb_seconds = module_usage_hourly.memory_mb_seconds + EXCLUDED.memory_mb_seconds,
+                storage_mb_seconds = module_usage_hourly.storage_mb_seconds + EXCLUDED.storage_mb_seconds,
+                api_calls = module_usage_hourly.api_calls + EXCLUDED.api_calls
+            "#,
+            usage.instance_id,
+            usage.period_start,
+            usage.module_id,
+            usage.tenant_id,
+            usage.cpu_seconds,
+            usage.memory_mb_seconds,
+            usage.storage_mb_seconds,
+            usage.api_calls as i64
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list_tenant_module_usage(
+        &self,
+        tenant_id: &str,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+    ) -> ModuleResult<Vec<ModuleUsage>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT instance_id, period_start, module_id, tenant_id,
+                   cpu_seconds, memory_mb_seconds, storage_mb_seconds, api_calls
+            FROM module_usage_hourly
+            WHERE tenant_id = $1 AND period_start >= $2 AND period_start < $3
+            ORDER BY period_start
+            "#,
+            tenant_id,
+            from,
+            to
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter()
+            .map(|row| ModuleUsage {
+                instance_id: row.instance_id,
+                module_id: row.module_id,
+                tenant_id: row.tenant_id,
+                period_start: row.period_start,
+                cpu_seconds: row.cpu_seconds,
+                memory_mb_seconds: row.memory_mb_seconds,
+                storage_mb_seconds: row.storage_mb_seconds,
+                api_calls: row.api_calls as u64,
+            })
+            .collect())
+    }
+
+    async fn list_unreported_module_usage(&self, before: chrono::DateTime<chrono::Utc>) -> ModuleResult<Vec<ModuleUsage>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT instance_id, period_start, module_id, tenant_id,
+                   cpu_seconds, memory_mb_seconds, storage_mb_seconds, api_calls
+            FROM module_usage_hourly
+            WHERE reported_at IS NULL AND period_start < $1
+            ORDER BY period_start
+            "#,
+            before
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter()
+            .map(|row| ModuleUsage {
+                instance_id: row.instance_id,
+                module_id: row.module_id,
+                tenant_id: row.tenant_id,
+                period_start: row.period_start,
+                cpu_seconds: row.cpu_seconds,
+                memory_mb_seconds: row.memory_mb_seconds,
+                storage_mb_seconds: row.storage_mb_seconds,
+                api_calls: row.api_calls as u64,
+            })
+            .collect())
+    }
+
+    async fn mark_module_usage_reported(
+        &self,
+        instance_id: Uuid,
+        period_start: chrono::DateTime<chrono::Utc>,
+    ) -> ModuleResult<()> {
+        sqlx::query!(
+            "UPDATE module_usage_hourly SET reported_at = NOW() WHERE instance_id = $1 AND period_start = $2",
+            instance_id,
+            period_start
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn save_module_assets(&self, bundle: Option<&FrontendBundle>, assets: &[ModuleAsset]) -> ModuleResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        for asset in assets {
+            sqlx::query!(
+                r#"
+                INSERT INTO module_assets (module_id, version, path, content_type, etag, size_bytes, content)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (module_id, version, path) DO NOTHING
+                "#,
+                asset.module_id,
+                asset.version.to_string(),
+                asset.path,
+                asset.content_type,
+                asset.etag,
+                asset.size_bytes as i64,
+                asset.content
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        if let Some(bundle) = bundle {
+            sqlx::query!(
+                r#"
+                INSERT INTO module_frontend_bundles (module_id, version, remote_entry, integrity, micro_frontends, created_at)
+                VALUES ($1, $2, $3, $4, $5, $6)
+                ON CONFLICT (module_id, version) DO NOTHING
+                "#,
+                bundle.module_id,
+                bundle.version.to_string(),
+                bundle.remote_entry,
+                bundle.integrity,
+                serde_json::to_value(&bundle.micro_frontends)?,
+                bundle.created_at
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_module_asset(
+        &self,
+        module_id: &str,
+        version: &semver::Version,
+        path: &str,
+    ) -> ModuleResult<Option<ModuleAsset>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT module_id, version, path, content_type, etag, size_bytes, content
+            FROM module_assets
+            WHERE module_id = $1 AND version = $2 AND path = $3
+            "#,
+            module_id,
+            version.to_string(),
+            path
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        Ok(row.map(|row| ModuleAsset {
+            module_id: row.module_id,
+            version: version.clone(),
+            path: row.path,
+            content_type: row.content_type,
+            etag: row.etag,
+            size_bytes: row.size_bytes as u64,
+            content: row.content,
+        }))
+    }
+
+    async fn get_frontend_bundle(&self, module_id: &str, version: &semver::Version) -> ModuleResult<Option<FrontendBundle>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT module_id, remote_entry, integrity, micro_frontends, created_at
+            FROM module_frontend_bundles
+            WHERE module_id = $1 AND version = $2
+            "#,
+            module_id,
+            version.to_string()
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        match row {
+            Some(row) => Ok(Some(FrontendBundle {
+                module_id: row.module_id,
+                version: version.clone(),
+                remote_entry: row.remote_entry,
+                integrity: row.integrity,
+                micro_frontends: serde_json::from_value(row.micro_frontends)?,
+                created_at: row.created_at,
+            })),
+            None => Ok(None),
+        }
+    }
+
+    async fn upsert_marketplace_listings(&self, listings: &[MarketplaceListing]) -> ModuleResult<()> {
+        let mut tx = self.pool.begin().await?;
+        for listing in listings {
+            let metadata = &listing.metadata;
+            let categories: Vec<String> = metadata.categories.iter().map(category_key).collect();
+
+            // Names weigh most, then keywords and categories, then descriptions
+            sqlx::query!(
+                r#"
+                INSERT INTO marketplace_listings (
+                    module_id, metadata, categories, pricing_model, price, currency,
+                    rating_average, review_count, install_count, search_vector, indexed_at
+                )
+                VALUES (
+                    $1, $2, $3, $4, $5, $6, $7, $8, $9,
+                    setweight(to_tsvector('english', $10), 'A')
+                        || setweight(to_tsvector('english', $11), 'B')
+                        || setweight(to_tsvector('english', $12), 'C'),
+                    NOW()
+                )
+                ON CONFLICT (module_id) DO UPDATE SET
+                    metadata = EXCLUDED.metadata,
+                    categories = EXCLUDED.categories,
+                    pricing_model = EXCLUDED.pricing_model,
+                    price = EXCLUDED.price,
+                    currency = EXCLUDED.currency,
+                    rating_average = EXCLUDED.rating_average,
+                    review_count = EXCLUDED.review_count,
+                    install_count = EXCLUDED.install_count,
+                    search_vector = EXCLUDED.search_vector,
+                    indexed_at = NOW()
+                "#,
+                metadata.id,
+                serde_json::to_value(metadata)?,
+                &categories,
+                pricing_key(&listing.pricing_model),
+                listing.price,
+                listing.currency,
+                listing.rating_average,
+                listing.review_count as i32,
+                listing.install_count as i64,
+                metadata.name,
+                format!("{} {}", metadata.keywords.join(" "), categories.join(" ")),
+                format!("{} {}", metadata.description, metadata.long_description.as_deref().unwrap_or_default())
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn delete_marketplace_listings(&self, module_ids: &[String]) -> ModuleResult<()> {
+        if module_ids.is_empty() {
+            return Ok(());
+        }
+        sqlx::query!(
+            "DELETE FROM marketplace_listings WHERE module_id = ANY($1)",
+            module_ids
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn search_marketplace_index(&self, text: Option<&str>, limit: u32) -> ModuleResult<Vec<IndexHit>> {
+        // Names are also matched as substrings, so partially typed names find
+        // their module before the query makes a whole word
+        let name_pattern = text.map(|text| {
+            let escaped = text.replace('\\', "\\\\").replace('%', "\\%").replace('_', "\\_");
+            format!("%{}%", escaped)
+        });
+        let rows = sqlx::query!(
+            r#"
+            SELECT metadata, pricing_model, price, currency, rating_average, review_count, install_count,
+                   COALESCE(ts_rank_cd(search_vector, websearch_to_tsquery('english', $1)), 0)::DOUBLE PRECISION
+                       + CASE WHEN metadata->>'name' ILIKE $2 THEN 0.1 ELSE 0 END AS "text_rank!"
+            FROM marketplace_listings
+            WHERE $1::TEXT IS NULL
+               OR search_vector @@ websearch_to_tsquery('english', $1)
+               OR metadata->>'name' ILIKE $2
+            ORDER BY "text_rank!" DESC, install_count DESC
+            LIMIT $3
+            "#,
+            text,
+            name_pattern,
+            limit as i64
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut hits = Vec::with_capacity(rows.len());
+        for row in rows {
+            hits.push(IndexHit {
+                listing: MarketplaceListing {
+                    metadata: serde_json::from_value(row.metadata)?,
+                    pricing_model: serde_json::from_value(serde_json::Value::String(row.pricing_model))?,
+                    price: row.price,
+                    currency: row.currency,
+                    rating_average: row.rating_average,
+                    review_count: row.review_count as u32,
+                    install_count: row.install_count as u64,
+                    delisted: false,
+                },
+                text_rank: if text.is_some() { row.text_rank } else { 0.0 },
+            });
+        }
+        Ok(hits)
+    }
+
+    async fn list_co_installed_modules(
+        &self,
+        module_ids: &[String],
+        exclude_tenant: &str,
+    ) -> ModuleResult<Vec<(String, u64)>> {
+        if module_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let rows = sqlx::query!(
+            r#"
+            SELECT other.module_id, COUNT(DISTINCT other.tenant_id) AS "tenants!"
+            FROM module_instances installed
+            JOIN module_instances other
+                ON other.tenant_id = installed.tenant_id AND other.module_id <> ALL($1)
+            WHERE installed.module_id = ANY($1) AND installed.tenant_id <> $2
+            GROUP BY other.module_id
+            "#,
+            module_ids,
+            exclude_tenant
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        Ok(rows.into_iter().map(|row| (row.module_id, row.tenants as u64)).collect())
+    }
+
+    async fn save_security_report(&self, report: &SecurityReport) -> ModuleResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO module_security_reports (
+                scan_id, module_id, version, checksum, score, issues, stages, review_status,
+                review_reasons, reviewer, review_notes, reviewed_at, scanned_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+            ON CONFLICT (scan_id) DO UPDATE SET
+                review_status = EXCLUDED.review_status,
+                reviewer = EXCLUDED.reviewer,
+                review_notes = EXCLUDED.review_notes,
+                reviewed_at = EXCLUDED.reviewed_at
+            "#,
+            report.scan_id,
+            report.module_id,
+            report.version.to_string(),
+            report.checksum,
+            report.score as i16,
+            serde_json::to_value(&report.issues)?,
+            serde_json::to_value(&report.stages)?,
+            format!("{:?}", report.review_status),
+            serde_json::to_value(&report.review_reasons)?,
+            report.reviewer,
+            report.review_notes,
+            report.reviewed_at,
+            report.scanned_at
+        )
+        .execute(&self.pool)
+        .await?;
+        Ok(())
+    }
+
+    async fn get_security_report(&self, scan_id: Uuid) -> ModuleResult<Option<SecurityReport>> {
+        let row = sqlx::query_as!(
+            SecurityReportRow,
+            r#"
+            SELECT scan_id, module_id, version, checksum, score, issues, stages, review_status,
+                   review_reasons, reviewer, review_notes, reviewed_at, scanned_at
+            FROM module_security_reports
+            WHERE scan_id = $1
+            "#,
+            scan_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(SecurityReportRow::into_report).transpose()
+    }
+
+    async fn find_security_report(
+        &self,
+        module_id: &str,
+        version: &semver::Version,
+        checksum: &str,
+    ) -> ModuleResult<Option<SecurityReport>> {
+        let row = sqlx::query_as!(
+            SecurityReportRow,
+            r#"
+            SELECT scan_id, module_id, version, checksum, score, issues, stages, review_status,
+                   review_reasons, reviewer, review_notes, reviewed_at, scanned_at
+            FROM module_security_reports
+            WHERE module_id = $1 AND version = $2 AND checksum = $3
+            ORDER BY scanned_at DESC
+            LIMIT 1
+            "#,
+            module_id,
+            version.to_string(),
+            checksum
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(SecurityReportRow::into_report).transpose()
+    }
+
+    async fn list_security_reports(&self, status: ReviewStatus) -> ModuleResult<Vec<SecurityReport>> {
+        let rows = sqlx::query_as!(
+            SecurityReportRow,
+            r#"
+            SELECT scan_id, module_id, version, checksum, score, issues, stages, review_status,
+                   review_reasons, reviewer, review_notes, reviewed_at, scanned_at
+            FROM module_security_reports
+            WHERE review_status = $1
+            ORDER BY scanned_at
+            "#,
+            format!("{:?}", status)
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(SecurityReportRow::into_report).collect()
+    }
+
+    async fn list_module_security_reports(&self, module_ids: &[String]) -> ModuleResult<Vec<SecurityReport>> {
+        if module_ids.is_empty() {
+            return Ok(vec![]);
+        }
+        let rows = sqlx::query_as!(
+            SecurityReportRow,
+            r#"
+            SELECT DISTINCT ON (module_id, version)
+                   scan_id, module_id, version, checksum, score, issues, stages, review_status,
+                   review_reasons, reviewer, review_notes, reviewed_at, scanned_at
+            FROM module_security_reports
+            WHERE module_id = ANY($1)
+            ORDER BY module_id, version, scanned_at DESC
+            "#,
+            module_ids
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(SecurityReportRow::into_report).collect()
+    }
+
+    async fn save_module_job(&self, job: &ModuleJob) -> ModuleResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO module_jobs (
+                id, instance_id, tenant_id, module_id, name, schedule, payload, timeout_seconds,
+                schedule_id, state, disabled_reason, disabled_by, consecutive_failures,
+                last_run_at, last_run_status, last_error, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18)
+            ON CONFLICT (id) DO UPDATE SET
+                schedule = EXCLUDED.schedule,
+                payload = EXCLUDED.payload,
+                timeout_seconds = EXCLUDED.timeout_seconds,
+                schedule_id = EXCLUDED.schedule_id,
+                state = EXCLUDED.state,
+                disabled_reason = EXCLUDED.disabled_reason,
+                disabled_by = EXCLUDED.disabled_by,
+                consecutive_failures = EXCLUDED.consecutive_failures,
+                last_run_at = EXCLUDED.last_run_at,
+                last_run_status = EXCLUDED.last_run_status,
+                last_error = EXCLUDED.last_error,
+                updated_at = EXCLUDED.updated_at
+            "#,
+            job.id,
+            job.instance_id,
+            job.tenant_id,
+            job.module_id,
+            job.name,
+            serde_json::to_value(&job.schedule)?,
+            job.payload,
+            job.timeout_seconds as i64,
+            job.schedule_id,
+            job.state.as_str(),
+            job.disabled_reason,
+            job.disabled_by,
+            job.consecutive_failures as i32,
+            job.last_run_at,
+            job.last_run_status.map(|status| status.as_str()),
+            job.last_error,
+            job.created_at,
+            job.updated_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_module_job(&self, job_id: Uuid) -> ModuleResult<Option<ModuleJob>> {
+        let row = sqlx::query_as!(
+            ModuleJobRow,
+            r#"
+            SELECT id, instance_id, tenant_id, module_id, name, schedule, payload, timeout_seconds,
+                   schedule_id, state, disabled_reason, disabled_by, consecutive_failures,
+                   last_run_at, last_run_status, last_error, created_at, updated_at
+            FROM module_jobs
+            WHERE id = $1
+            "#,
+            job_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(ModuleJobRow::into_job).transpose()
+    }
+
+    async fn find_module_job(&self, instance_id: Uuid, name: &str) -> ModuleResult<Option<ModuleJob>> {
+        let row = sqlx::query_as!(
+            ModuleJobRow,
+            r#"
+            SELECT id, instance_id, tenant_id, module_id, name, schedule, payload, timeout_seconds,
+                   schedule_id, state, disabled_reason, disabled_by, consecutive_failures,
+                   last_run_at, last_run_status, last_error, created_at, updated_at
+            FROM module_jobs
+            WHERE instance_id = $1 AND name = $2
+            "#,
+            instance_id,
+            name
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.map(ModuleJobRow::into_job).transpose()
+    }
+
+    async fn list_instance_jobs(&self, instance_id: Uuid) -> ModuleResult<Vec<ModuleJob>> {
+        let rows = sqlx::query_as!(
+            ModuleJobRow,
+            r#"
+            SELECT id, instance_id, tenant_id, module_id, name, schedule, payload, timeout_seconds,
+                   schedule_id, state, disabled_reason, disabled_by, consecutive_failures,
+                   last_run_at, last_run_status, last_error, created_at, updated_at
+            FROM module_jobs
+            WHERE instance_id = $1
+            ORDER BY name
+            "#,
+            instance_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(ModuleJobRow::into_job).collect()
+    }
+
+    async fn list_tenant_jobs(&self, tenant_id: &str) -> ModuleResult<Vec<ModuleJob>> {
+        let rows = sqlx::query_as!(
+            ModuleJobRow,
+            r#"
+            SELECT id, instance_id, tenant_id, module_id, name, schedule, payload, timeout_seconds,
+                   schedule_id, state, disabled_reason, disabled_by, consecutive_failures,
+                   last_run_at, last_run_status, last_error, created_at, updated_at
+            FROM module_jobs
+            WHERE tenant_id = $1
+            ORDER BY module_id, name
+            "#,
+            tenant_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter().map(ModuleJobRow::into_job).collect()
+    }
+
+    async fn delete_module_job(&self, job_id: Uuid) -> ModuleResult<()> {
+        sqlx::query!("DELETE FROM module_jobs WHERE id = $1", job_id)
+            .execute(&self.pool)
+            .await?;
+
+        Ok(())
+    }
+
+    async fn save_job_run(&self, run: &JobRun) -> ModuleResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO module_job_runs (id, job_id, instance_id, tenant_id, status, error, started_at, finished_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (id) DO UPDATE SET
+                status = EXCLUDED.status,
+                error = EXCLUDED.error,
+                finished_at = EXCLUDED.finished_at
+            "#,
+            run.id,
+            run.job_id,
+            run.instance_id,
+            run.tenant_id,
+            run.status.as_str(),
+            run.error,
+            run.started_at,
+            run.finished_at
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn list_job_runs(&self, job_id: Uuid, limit: u32) -> ModuleResult<Vec<JobRun>> {
+        let rows = sqlx::query!(
+            r#"
+            SELECT id, job_id, instance_id, tenant_id, status, error, started_at, finished_at
+            FROM module_job_runs
+            WHERE job_id = $1
+            ORDER BY started_at DESC
+            LIMIT $2
+            "#,
+            job_id,
+            limit as i64
+        )
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.into_iter()
+            .map(|row| Ok(JobRun {
+                id: row.id,
+                job_id: row.job_id,
+                instance_id: row.instance_id,
+                tenant_id: row.tenant_id,
+                status: JobRunStatus::parse(&row.status)?,
+                error: row.error,
+                started_at: row.started_at,
+                finished_at: row.finished_at,
+            }))
+            .collect()
+    }
+
+    async fn count_running_job_runs(&self, instance_id: Uuid, since: chrono::DateTime<chrono::Utc>) -> ModuleResult<u32> {
+        let count = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) AS "count!"
+            FROM module_job_runs
+            WHERE instance_id = $1 AND status = 'running' AND started_at > $2
+            "#,
+            instance_id,
+            since
+        )
+        .fetch_one(&self.pool)
+        .await?;
+
+        Ok(count as u32)
+    }
+
+    async fn prune_job_runs(&self, job_id: Uuid, keep: u32) -> ModuleResult<()> {
+        sqlx::query!(
+            r#"
+            DELETE FROM module_job_runs
+            WHERE job_id = $1 AND id NOT IN (
+                SELECT id FROM module_job_runs WHERE job_id = $1 ORDER BY started_at DESC LIMIT $2
+            )
+            "#,
+            job_id,
+            keep as i64
+        )
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+struct ModuleJobRow {
+    id: Uuid,
+    instance_id: Uuid,
+    tenant_id: String,
+    module_id: String,
+    name: String,
+    schedule: serde_json::Value,
+    payload: serde_json::Value,
+    timeout_seconds: i64,
+    schedule_id: Option<String>,
+    state: String,
+    disabled_reason: Option<String>,
+    disabled_by: Option<String>,
+    consecutive_failures: i32,
+    last_run_at: Option<chrono::DateTime<chrono::Utc>>,
+    last_run_status: Option<String>,
+    last_error: Option<String>,
+    created_at: chrono::DateTime<chrono::Utc>,
+    updated_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl ModuleJobRow {
+    fn into_job(self) -> ModuleResult<ModuleJob> {
+        Ok(ModuleJob {
+            id: self.id,
+            instance_id: self.instance_id,
+            tenant_id: self.tenant_id,
+            module_id: self.module_id,
+            name: self.name,
+            schedule: serde_json::from_value(self.schedule)?,
+            payload: self.payload,
+            timeout_seconds: self.timeout_seconds as u64,
+            schedule_id: self.schedule_id,
+            state: JobState::parse(&self.state)?,
+            disabled_reason: self.disabled_reason,
+            disabled_by: self.disabled_by,
+            consecutive_failures: self.consecutive_failures as u32,
+            last_run_at: self.last_run_at,
+            last_run_status: self.last_run_status.as_deref().map(JobRunStatus::parse).transpose()?,
+            last_error: self.last_error,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        })
+    }
+}
+
+struct SecurityReportRow {
+    scan_id: Uuid,
+    module_id: String,
+    version: String,
+    checksum: String,
+    score: i16,
+    issues: serde_json::Value,
+    stages: serde_json::Value,
+    review_status: String,
+    review_reasons: serde_json::Value,
+    reviewer: Option<String>,
+    review_notes: Option<String>,
+    reviewed_at: Option<chrono::DateTime<chrono::Utc>>,
+    scanned_at: chrono::DateTime<chrono::Utc>,
+}
+
+impl SecurityReportRow {
+    fn into_report(self) -> ModuleResult<SecurityReport> {
+        Ok(SecurityReport {
+            scan_id: self.scan_id,
+            version: semver::Version::parse(&self.version)
+                .map_err(|e| ModuleError::SerializationError(format!("Invalid version {}: {}", self.version, e)))?,
+            module_id: self.module_id,
+            checksum: self.checksum,
+            score: self.score as u8,
+            issues: serde_json::from_value(self.issues)?,
+            stages: serde_json::from_value(self.stages)?,
+            review_status: serde_json::from_value(serde_json::Value::String(self.review_status))?,
+            review_reasons: serde_json::from_value(self.review_reasons)?,
+            reviewer: self.reviewer,
+            review_notes: self.review_notes,
+            reviewed_at: self.reviewed_at,
+            scanned_at: self.scanned_at,
+        })
+    }
+}
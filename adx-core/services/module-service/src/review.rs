@@ -0,0 +1,112 @@
+use std::sync::Arc;
+use chrono::Utc;
+use serde::{Deserialize, Serialize};
+use tracing::info;
+use uuid::Uuid;
+
+use crate::{ModuleResult, ModuleError, ModulePackage, ModuleRepository};
+use crate::security::{ModuleSecurityScanner, ReviewStatus, SecurityReport};
+
+/// A reviewer's decision on a package the scan held back
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReviewDecision {
+    pub approved: bool,
+    pub reviewer: String,
+    pub notes: Option<String>,
+}
+
+/// Scans packages before they install and keeps the reports, so the ones
+/// the scan holds back wait for a reviewer and a version is scanned once
+pub struct SecurityReviewQueue {
+    scanner: Arc<ModuleSecurityScanner>,
+    repository: Arc<dyn ModuleRepository>,
+}
+
+impl SecurityReviewQueue {
+    pub fn new(scanner: Arc<ModuleSecurityScanner>, repository: Arc<dyn ModuleRepository>) -> Self {
+        Self { scanner, repository }
+    }
+
+    /// The package's report, scanning it if this content has not been
+    /// scanned before
+    pub async fn report_for(&self, package: &ModulePackage) -> ModuleResult<SecurityReport> {
+        let existing = self.repository.find_security_report(
+            &package.metadata.id,
+            &package.metadata.version,
+            &package.checksum,
+        ).await?;
+        if let Some(report) = existing {
+            return Ok(report);
+        }
+
+        let report = self.scanner.scan(package).await?;
+        self.repository.save_security_report(&report).await?;
+        if report.review_status == ReviewStatus::Pending {
+            info!(
+                "{} {} is waiting for security review: {}",
+                report.module_id, report.version, report.review_reasons.join("; ")
+            );
+        }
+        Ok(report)
+    }
+
+    /// Fail unless the package passed its scan or a reviewer approved it
+    pub async fn admit(&self, package: &ModulePackage) -> ModuleResult<SecurityReport> {
+        let report = self.report_for(package).await?;
+        match report.review_status {
+            status if status.admits() => Ok(report),
+            ReviewStatus::Pending => Err(ModuleError::SecurityScanFailed(format!(
+                "{} {} is waiting for security review (scan {})",
+                report.module_id, report.version, report.scan_id
+            ))),
+            _ => Err(ModuleError::SecurityScanFailed(format!(
+                "{} {} was rejected by security review (scan {}): {}",
+                report.module_id,
+                report.version,
+                report.scan_id,
+                report.review_reasons.join("; ")
+            ))),
+        }
+    }
+
+    pub async fn get(&self, scan_id: Uuid) -> ModuleResult<SecurityReport> {
+        self.repository.get_security_report(scan_id).await?
+            .ok_or_else(|| ModuleError::NotFound(format!("Security report {}", scan_id)))
+    }
+
+    /// Reports by review status, oldest first so the queue is worked in order
+    pub async fn list(&self, status: ReviewStatus) -> ModuleResult<Vec<SecurityReport>> {
+        self.repository.list_security_reports(status).await
+    }
+
+    /// Approve or reject a package waiting for review. Packages the scan
+    /// rejected outright are not reviewable.
+    pub async fn decide(&self, scan_id: Uuid, decision: ReviewDecision) -> ModuleResult<SecurityReport> {
+        if decision.reviewer.trim().is_empty() {
+            return Err(ModuleError::ValidationFailed("A review needs a reviewer".to_string()));
+        }
+
+        let mut report = self.get(scan_id).await?;
+        if report.review_status != ReviewStatus::Pending {
+            return Err(ModuleError::ValidationFailed(format!(
+                "Scan {} is not waiting for review; it is {:?}", scan_id, report.review_status
+            )));
+        }
+
+        report.review_status = if decision.approved { ReviewStatus::Approved } else { ReviewStatus::Rejected };
+        report.reviewer = Some(decision.reviewer);
+        report.review_notes = decision.notes;
+        report.reviewed_at = Some(Utc::now());
+        self.repository.save_security_report(&report).await?;
+
+        info!(
+            "{} {} {:?} by {} (scan {})",
+            report.module_id,
+            report.version,
+            report.review_status,
+            report.reviewer.as_deref().unwrap_or_default(),
+            scan_id
+        );
+        Ok(report)
+    }
+}
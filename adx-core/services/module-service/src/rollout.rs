@@ -0,0 +1,499 @@
+use std::sync::Arc;
+use std::time::Duration;
+use chrono::{DateTime, Utc};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tokio::sync::RwLock;
+use tracing::{info, warn, error};
+use uuid::Uuid;
+
+use crate::{
+    ModuleResult, ModuleError, ModuleManager, ModuleRepository, ModuleInstance, HealthStatus,
+    UpdateModuleRequest,
+};
+
+/// How an update is rolled out across the tenants that have a module
+/// installed: a share of instances at a time, checking their health before
+/// moving on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RolloutPolicy {
+    /// Cumulative share of instances updated by the end of each stage; the
+    /// last stage must reach 100
+    pub stages: Vec<RolloutStage>,
+    /// Tenants updated in the first stage, ahead of everyone else
+    #[serde(default)]
+    pub canary_tenants: Vec<String>,
+    pub thresholds: RolloutThresholds,
+    pub on_failure: RolloutFailureAction,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RolloutStage {
+    pub percentage: u8,
+    /// How long updated instances run before their health is checked
+    pub soak_seconds: u64,
+}
+
+/// Health the updated instances must stay within for the rollout to proceed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RolloutThresholds {
+    /// Share of updated instances that may be unhealthy or have failed to update
+    pub max_unhealthy_percentage: f64,
+    /// Mean errors reported per updated instance
+    pub max_errors_per_instance: f64,
+    #[serde(default)]
+    pub max_response_time_ms: Option<u64>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RolloutFailureAction {
+    /// Stop and wait for an operator to resume or roll back
+    Pause,
+    /// Return every updated instance to its previous version
+    Rollback,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RolloutStatus {
+    Running,
+    Paused,
+    RollingBack,
+    Completed,
+    RolledBack,
+}
+
+impl RolloutStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RolloutStatus::Running => "running",
+            RolloutStatus::Paused => "paused",
+            RolloutStatus::RollingBack => "rolling_back",
+            RolloutStatus::Completed => "completed",
+            RolloutStatus::RolledBack => "rolled_back",
+        }
+    }
+
+    pub fn parse(status: &str) -> ModuleResult<Self> {
+        match status {
+            "running" => Ok(RolloutStatus::Running),
+            "paused" => Ok(RolloutStatus::Paused),
+            "rolling_back" => Ok(RolloutStatus::RollingBack),
+            "completed" => Ok(RolloutStatus::Completed),
+            "rolled_back" => Ok(RolloutStatus::RolledBack),
+            other => Err(ModuleError::SerializationError(format!("Unknown rollout status {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RolloutInstanceState {
+    Pending,
+    Updated,
+    Failed,
+    /// The new version needs capabilities the tenant has not consented to;
+    /// left on its version and not counted against the rollout
+    AwaitingConsent,
+    RolledBack,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RolloutInstance {
+    pub instance_id: Uuid,
+    pub tenant_id: String,
+    pub previous_version: Version,
+    pub stage: usize,
+    pub state: RolloutInstanceState,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleRollout {
+    pub id: Uuid,
+    pub module_id: String,
+    pub target_version: Version,
+    pub policy: RolloutPolicy,
+    pub status: RolloutStatus,
+    /// Index of the stage to run next
+    pub next_stage: usize,
+    pub instances: Vec<RolloutInstance>,
+    /// Why the rollout was paused or rolled back
+    pub reason: Option<String>,
+    pub initiated_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartRolloutRequest {
+    pub module_id: String,
+    pub target_version: Version,
+    pub policy: RolloutPolicy,
+    pub initiated_by: String,
+}
+
+impl RolloutPolicy {
+    pub fn validate(&self) -> ModuleResult<()> {
+        if self.stages.is_empty() {
+            return Err(ModuleError::ValidationFailed("Rollout policy needs at least one stage".to_string()));
+        }
+
+        let mut previous = 0;
+        for stage in &self.stages {
+            if stage.percentage <= previous || stage.percentage > 100 {
+                return Err(ModuleError::ValidationFailed(
+                    "Rollout stage percentages must increase up to 100".to_string()
+                ));
+            }
+            previous = stage.percentage;
+        }
+        if previous != 100 {
+            return Err(ModuleError::ValidationFailed("The last rollout stage must reach 100%".to_string()));
+        }
+
+        let thresholds = &self.thresholds;
+        if !(0.0..=100.0).contains(&thresholds.max_unhealthy_percentage) || thresholds.max_errors_per_instance < 0.0 {
+            return Err(ModuleError::ValidationFailed("Rollout thresholds are out of range".to_string()));
+        }
+
+        Ok(())
+    }
+}
+
+/// The instances to update, in rollout order, with the stage each is
+/// updated in. Canary tenants come first; the rest are ordered by a hash of
+/// the tenant id, so a tenant lands in the same cohort on every rollout.
+pub fn plan_instances(instances: Vec<ModuleInstance>, policy: &RolloutPolicy, target_version: &Version) -> Vec<RolloutInstance> {
+    let mut instances: Vec<ModuleInstance> = instances.into_iter()
+        .filter(|instance| instance.version != *target_version)
+        .collect();
+    instances.sort_by_cached_key(|instance| {
+        (!policy.canary_tenants.contains(&instance.tenant_id), tenant_cohort(&instance.tenant_id), instance.tenant_id.clone())
+    });
+
+    let total = instances.len();
+    instances.into_iter()
+        .enumerate()
+        .map(|(position, instance)| {
+            let stage = if policy.canary_tenants.contains(&instance.tenant_id) {
+                0
+            } else {
+                policy.stages.iter()
+                    .position(|stage| position < (total * stage.percentage as usize).div_ceil(100))
+                    .unwrap_or(policy.stages.len() - 1)
+            };
+
+            RolloutInstance {
+                instance_id: instance.id,
+                tenant_id: instance.tenant_id,
+                previous_version: instance.version,
+                stage,
+                state: RolloutInstanceState::Pending,
+                error: None,
+            }
+        })
+        .collect()
+}
+
+fn tenant_cohort(tenant_id: &str) -> u64 {
+    let digest = Sha256::digest(tenant_id.as_bytes());
+    let mut prefix = [0u8; 8];
+    prefix.copy_from_slice(&digest[..8]);
+    u64::from_be_bytes(prefix)
+}
+
+/// Health of the instances updated so far
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RolloutHealth {
+    pub instances: usize,
+    pub unhealthy: usize,
+    pub mean_errors: f64,
+    pub max_response_time_ms: u64,
+}
+
+impl RolloutHealth {
+    /// `None` stands for an instance that failed to update or report health
+    pub fn measure(health: &[Option<HealthStatus>]) -> Self {
+        let reported: Vec<&HealthStatus> = health.iter().flatten().collect();
+        let errors: u64 = reported.iter().map(|status| status.error_count as u64).sum();
+
+        Self {
+            instances: health.len(),
+            unhealthy: health.len() - reported.iter().filter(|status| status.is_healthy).count(),
+            mean_errors: if reported.is_empty() { 0.0 } else { errors as f64 / reported.len() as f64 },
+            max_response_time_ms: reported.iter().map(|status| status.response_time_ms).max().unwrap_or(0),
+        }
+    }
+
+    /// The threshold this health breaches, if any
+    pub fn breach(&self, thresholds: &RolloutThresholds) -> Option<String> {
+        if self.instances == 0 {
+            return None;
+        }
+
+        let unhealthy_percentage = self.unhealthy as f64 * 100.0 / self.instances as f64;
+        if unhealthy_percentage > thresholds.max_unhealthy_percentage {
+            return Some(format!(
+                "{} of {} updated instances are unhealthy ({:.1}% > {:.1}%)",
+                self.unhealthy, self.instances, unhealthy_percentage, thresholds.max_unhealthy_percentage
+            ));
+        }
+        if self.mean_errors > thresholds.max_errors_per_instance {
+            return Some(format!(
+                "Updated instances report {:.1} errors each (> {:.1})",
+                self.mean_errors, thresholds.max_errors_per_instance
+            ));
+        }
+        if let Some(max_response_time_ms) = thresholds.max_response_time_ms {
+            if self.max_response_time_ms > max_response_time_ms {
+                return Some(format!(
+                    "Updated instances respond in up to {}ms (> {}ms)",
+                    self.max_response_time_ms, max_response_time_ms
+                ));
+            }
+        }
+        None
+    }
+}
+
+/// Runs rollouts in the background. Progress is saved after every stage and
+/// status changes are compare-and-set, so an operator's pause is never lost
+/// and a restarted service picks up where it left off.
+pub struct RolloutController {
+    manager: Arc<RwLock<ModuleManager>>,
+    repository: Arc<dyn ModuleRepository>,
+}
+
+impl RolloutController {
+    pub fn new(manager: Arc<RwLock<ModuleManager>>, repository: Arc<dyn ModuleRepository>) -> Self {
+        Self { manager, repository }
+    }
+
+    pub async fn start(self: &Arc<Self>, request: StartRolloutRequest) -> ModuleResult<ModuleRollout> {
+        request.policy.validate()?;
+
+        let rollouts = self.repository.list_module_rollouts(&request.module_id).await?;
+        if let Some(active) = rollouts.iter().find(|rollout| !is_finished(rollout.status)) {
+            return Err(ModuleError::AlreadyExists(format!(
+                "Rollout {} of {} is {}", active.id, request.module_id, active.status.as_str()
+            )));
+        }
+
+        let instances = self.repository.list_module_instances(&request.module_id).await?;
+        let instances = plan_instances(instances, &request.policy, &request.target_version);
+        if instances.is_empty() {
+            return Err(ModuleError::ValidationFailed(format!(
+                "No instances of {} need updating to {}", request.module_id, request.target_version
+            )));
+        }
+
+        let now = Utc::now();
+        let rollout = ModuleRollout {
+            id: Uuid::new_v4(),
+            module_id: request.module_id,
+            target_version: request.target_version,
+            policy: request.policy,
+            status: RolloutStatus::Running,
+            next_stage: 0,
+            instances,
+            reason: None,
+            initiated_by: request.initiated_by,
+            created_at: now,
+            updated_at: now,
+        };
+        self.repository.create_rollout(&rollout).await?;
+
+        info!("Started rollout {} of {} {} to {} instances",
+              rollout.id, rollout.module_id, rollout.target_version, rollout.instances.len());
+        self.spawn(rollout.id, RolloutStatus::Running);
+        Ok(rollout)
+    }
+
+    pub async fn get(&self, rollout_id: Uuid) -> ModuleResult<ModuleRollout> {
+        self.repository.get_rollout(rollout_id).await?
+            .ok_or_else(|| ModuleError::NotFound(format!("Rollout {}", rollout_id)))
+    }
+
+    pub async fn list(&self, module_id: &str) -> ModuleResult<Vec<ModuleRollout>> {
+        self.repository.list_module_rollouts(module_id).await
+    }
+
+    /// Stop after the current stage
+    pub async fn pause(&self, rollout_id: Uuid) -> ModuleResult<ModuleRollout> {
+        self.transition(rollout_id, RolloutStatus::Running, RolloutStatus::Paused, Some("Paused by operator")).await?;
+        self.get(rollout_id).await
+    }
+
+    pub async fn resume(self: &Arc<Self>, rollout_id: Uuid) -> ModuleResult<ModuleRollout> {
+        self.transition(rollout_id, RolloutStatus::Paused, RolloutStatus::Running, None).await?;
+        self.spawn(rollout_id, RolloutStatus::Running);
+        self.get(rollout_id).await
+    }
+
+    /// Return the instances updated so far to their previous version. Only a
+    /// paused rollout can be rolled back.
+    pub async fn rollback(self: &Arc<Self>, rollout_id: Uuid) -> ModuleResult<ModuleRollout> {
+        self.transition(rollout_id, RolloutStatus::Paused, RolloutStatus::RollingBack, Some("Rolled back by operator")).await?;
+        self.spawn(rollout_id, RolloutStatus::RollingBack);
+        self.get(rollout_id).await
+    }
+
+    /// Continue the rollouts a previous run of the service left in progress
+    pub async fn resume_interrupted(self: &Arc<Self>) -> ModuleResult<()> {
+        for rollout in self.repository.list_rollouts_in_progress().await? {
+            info!("Continuing rollout {} of {}", rollout.id, rollout.module_id);
+            self.spawn(rollout.id, rollout.status);
+        }
+        Ok(())
+    }
+
+    fn spawn(self: &Arc<Self>, rollout_id: Uuid, status: RolloutStatus) {
+        let controller = self.clone();
+        tokio::spawn(async move {
+            let result = match status {
+                RolloutStatus::RollingBack => controller.roll_back(rollout_id).await,
+                _ => controller.run(rollout_id).await,
+            };
+            if let Err(e) = result {
+                error!("Rollout {} failed: {}", rollout_id, e);
+            }
+        });
+    }
+
+    async fn run(&self, rollout_id: Uuid) -> ModuleResult<()> {
+        loop {
+            let mut rollout = self.get(rollout_id).await?;
+            if rollout.status != RolloutStatus::Running {
+                return Ok(());
+            }
+
+            let Some(stage) = rollout.policy.stages.get(rollout.next_stage).cloned() else {
+                self.repository.transition_rollout(rollout_id, RolloutStatus::Running, RolloutStatus::Completed, None).await?;
+                info!("Completed rollout {} of {} {}", rollout_id, rollout.module_id, rollout.target_version);
+                return Ok(());
+            };
+
+            info!("Rollout {} stage {}: updating {}% of instances", rollout_id, rollout.next_stage + 1, stage.percentage);
+            let target_version = rollout.target_version.clone();
+            let stage_index = rollout.next_stage;
+            for instance in rollout.instances.iter_mut()
+                .filter(|instance| instance.stage == stage_index && instance.state == RolloutInstanceState::Pending)
+            {
+                match self.update_instance(instance.instance_id, &target_version).await {
+                    Ok(()) => instance.state = RolloutInstanceState::Updated,
+                    Err(ModuleError::PermissionDenied(msg)) => {
+                        instance.state = RolloutInstanceState::AwaitingConsent;
+                        instance.error = Some(msg);
+                    }
+                    Err(e) => {
+                        warn!("Rollout {} failed to update instance {}: {}", rollout_id, instance.instance_id, e);
+                        instance.state = RolloutInstanceState::Failed;
+                        instance.error = Some(e.to_string());
+                    }
+                }
+            }
+            self.save_progress(&mut rollout).await?;
+
+            tokio::time::sleep(Duration::from_secs(stage.soak_seconds)).await;
+
+            let health = self.measure(&rollout).await;
+            if let Some(reason) = health.breach(&rollout.policy.thresholds) {
+                warn!("Rollout {} stage {} breached its thresholds: {}", rollout_id, stage_index + 1, reason);
+                // An operator may have paused the rollout during the soak
+                let to = match rollout.policy.on_failure {
+                    RolloutFailureAction::Pause => RolloutStatus::Paused,
+                    RolloutFailureAction::Rollback => RolloutStatus::RollingBack,
+                };
+                let transitioned = self.repository
+                    .transition_rollout(rollout_id, RolloutStatus::Running, to, Some(&reason))
+                    .await?;
+                if transitioned && to == RolloutStatus::RollingBack {
+                    return self.roll_back(rollout_id).await;
+                }
+                return Ok(());
+            }
+
+            rollout.next_stage += 1;
+            self.save_progress(&mut rollout).await?;
+        }
+    }
+
+    async fn roll_back(&self, rollout_id: Uuid) -> ModuleResult<()> {
+        let mut rollout = self.get(rollout_id).await?;
+        for instance in rollout.instances.iter_mut().filter(|instance| {
+            matches!(instance.state, RolloutInstanceState::Updated | RolloutInstanceState::Failed)
+        }) {
+            match self.update_instance(instance.instance_id, &instance.previous_version).await {
+                Ok(()) => {
+                    instance.state = RolloutInstanceState::RolledBack;
+                    instance.error = None;
+                }
+                Err(e) => {
+                    error!("Rollout {} failed to roll back instance {}: {}", rollout_id, instance.instance_id, e);
+                    instance.error = Some(e.to_string());
+                }
+            }
+        }
+        self.save_progress(&mut rollout).await?;
+
+        self.repository.transition_rollout(rollout_id, RolloutStatus::RollingBack, RolloutStatus::RolledBack, None).await?;
+        info!("Rolled back rollout {} of {}", rollout_id, rollout.module_id);
+        Ok(())
+    }
+
+    async fn update_instance(&self, instance_id: Uuid, version: &Version) -> ModuleResult<()> {
+        let manager = self.manager.read().await;
+        manager.update_module(UpdateModuleRequest {
+            instance_id,
+            target_version: Some(version.clone()),
+            preserve_config: true,
+            backup_current: true,
+            consent: None,
+        }).await?;
+        Ok(())
+    }
+
+    async fn measure(&self, rollout: &ModuleRollout) -> RolloutHealth {
+        let manager = self.manager.read().await;
+        let mut health = Vec::new();
+        for instance in &rollout.instances {
+            match instance.state {
+                RolloutInstanceState::Updated => {
+                    health.push(manager.get_module_health(instance.instance_id).await.ok());
+                }
+                RolloutInstanceState::Failed => health.push(None),
+                _ => {}
+            }
+        }
+        RolloutHealth::measure(&health)
+    }
+
+    async fn save_progress(&self, rollout: &mut ModuleRollout) -> ModuleResult<()> {
+        rollout.updated_at = Utc::now();
+        self.repository.save_rollout_progress(rollout).await
+    }
+
+    async fn transition(
+        &self,
+        rollout_id: Uuid,
+        from: RolloutStatus,
+        to: RolloutStatus,
+        reason: Option<&str>,
+    ) -> ModuleResult<()> {
+        if self.repository.transition_rollout(rollout_id, from, to, reason).await? {
+            return Ok(());
+        }
+
+        let rollout = self.get(rollout_id).await?;
+        Err(ModuleError::ValidationFailed(format!(
+            "Rollout {} is {}, not {}", rollout_id, rollout.status.as_str(), from.as_str()
+        )))
+    }
+}
+
+fn is_finished(status: RolloutStatus) -> bool {
+    matches!(status, RolloutStatus::Completed | RolloutStatus::RolledBack)
+}
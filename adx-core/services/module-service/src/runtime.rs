@@ -11,6 +11,9 @@ use crate::{
     loader::ModuleLoaderRegistry, activities::ModuleActivities, workflows::*,
 };
 
+/// Event deliveries handed to subscribers per tick of the delivery task
+const EVENT_DELIVERY_BATCH: u32 = 100;
+
 /// Module service runtime that orchestrates all module operations
 pub struct ModuleServiceRuntime {
     config: ModuleServiceConfig,
@@ -21,6 +24,14 @@ pub struct ModuleServiceRuntime {
     security_scanner: Arc<SecurityImpl>,
     loader_registry: Arc<ModuleLoaderRegistry>,
     activities: Arc<ModuleActivities>,
+    rollouts: Arc<crate::RolloutController>,
+    events: Arc<crate::EventBus>,
+    meter: Arc<crate::UsageMeter>,
+    billing: Arc<crate::BillingFeed>,
+    assets: Arc<crate::ModuleAssetStore>,
+    search: Arc<crate::MarketplaceIndex>,
+    security_reviews: Arc<crate::SecurityReviewQueue>,
+    jobs: Arc<crate::ModuleJobScheduler>,
 }
 
 impl ModuleServiceRuntime {
@@ -90,9 +101,52 @@ impl ModuleServiceRuntime {
             enable_configuration_analysis: config.security.enable_security_scanning,
             scan_timeout_seconds: config.security.scan_timeout_seconds,
             max_file_size_mb: 100,
-            vulnerability_db_url: "https://vulndb.adxcore.com".to_string(),
+            vulnerability_db_url: config.security.vulnerability_db_url.clone(),
+            min_security_score: config.security.min_security_score,
+            allowed_permissions: config.security.allowed_permissions.clone(),
+            blocked_permissions: config.security.blocked_permissions.clone(),
         };
         let security_scanner = Arc::new(SecurityImpl::new(security_config));
+        let security_reviews = Arc::new(crate::SecurityReviewQueue::new(
+            security_scanner.clone(),
+            repository.clone(),
+        ));
+
+        // Initialize package verifier
+        let verifier = Arc::new(crate::PackageVerifier::new(
+            crate::signing::PackageVerifierConfig {
+                require_signatures: config.security.require_signed_packages,
+                marketplace_keys: config.security.marketplace_signing_keys.clone(),
+            },
+            repository.clone(),
+        ));
+
+        // Initialize event bus
+        let events = Arc::new(crate::EventBus::new(config.events.clone(), repository.clone()));
+
+        // Initialize usage metering
+        let meter = Arc::new(crate::UsageMeter::new(repository.clone()));
+        let billing = Arc::new(crate::BillingFeed::new(
+            config.metering.clone(),
+            repository.clone(),
+            marketplace.clone(),
+        ));
+
+        // Initialize frontend asset store
+        let assets = Arc::new(crate::ModuleAssetStore::new(config.assets.clone(), repository.clone()));
+
+        // Initialize marketplace search index
+        let search = Arc::new(crate::MarketplaceIndex::new(
+            config.search.clone(),
+            repository.clone(),
+            marketplace.clone(),
+        )?);
+
+        // Initialize lifecycle hook runner
+        let hooks = Arc::new(crate::LifecycleHookRunner::new(config.hooks.clone(), sandbox.clone()));
+
+        // Initialize background job scheduler
+        let jobs = Arc::new(crate::ModuleJobScheduler::new(config.jobs.clone(), repository.clone()));
 
         // Initialize loader registry
         let loader_registry = Arc::new(ModuleLoaderRegistry::new());
@@ -112,16 +166,27 @@ impl ModuleServiceRuntime {
         let manager = Arc::new(RwLock::new(ModuleManager::new(
             repository.clone(),
             sandbox.clone(),
-            security_scanner.clone(),
+            security_reviews.clone(),
+            verifier.clone(),
+            events.clone(),
+            meter.clone(),
+            assets.clone(),
+            hooks.clone(),
+            jobs.clone(),
             manager_config,
         )));
 
+        // Initialize rollout controller
+        let rollouts = Arc::new(crate::RolloutController::new(manager.clone(), repository.clone()));
+
         // Initialize activities
         let activities = Arc::new(ModuleActivities::new(
             repository.clone(),
             marketplace.clone(),
             sandbox.clone(),
             security_scanner.clone(),
+            verifier,
+            hooks,
         ));
 
         Ok(Self {
@@ -133,6 +198,14 @@ impl ModuleServiceRuntime {
             security_scanner,
             loader_registry,
             activities,
+            rollouts,
+            events,
+            meter,
+            billing,
+            assets,
+            search,
+            security_reviews,
+            jobs,
         })
     }
 
@@ -151,6 +224,9 @@ impl ModuleServiceRuntime {
         // Start background tasks
         self.start_background_tasks().await?;
 
+        // Continue rollouts interrupted by a restart
+        self.rollouts.resume_interrupted().await?;
+
         info!("Module Service Runtime started successfully");
         Ok(())
     }
@@ -226,23 +302,97 @@ impl ModuleServiceRuntime {
             }
         });
 
-        // Start marketplace sync
+        // Start marketplace search index sync
+        if self.config.search.enabled {
+            let search = self.search.clone();
+            let sync_interval = self.config.search.sync_interval_seconds;
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(
+                    std::time::Duration::from_secs(sync_interval)
+                );
+
+                loop {
+                    interval.tick().await;
+                    
+                    if let Err(e) = search.sync().await {
+                        error!("Failed to sync marketplace search index: {}", e);
+                    }
+                }
+            });
+        }
+
+        // Start revocation list sync
         let marketplace = self.marketplace.clone();
+        let repository = self.repository.clone();
+        let sync_interval = self.config.security.revocation_sync_interval_seconds;
         tokio::spawn(async move {
             let mut interval = tokio::time::interval(
-                std::time::Duration::from_secs(3600) // Sync every hour
+                std::time::Duration::from_secs(sync_interval)
             );
 
             loop {
                 interval.tick().await;
                 
-                // Sync with marketplace
-                if let Err(e) = marketplace.get_featured().await {
-                    error!("Failed to sync with marketplace: {}", e);
+                if let Err(e) = Self::sync_revocations(&marketplace, &repository).await {
+                    error!("Failed to sync revocation list: {}", e);
                 }
             }
         });
 
+        // Start event bus delivery
+        let manager = self.manager.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(
+                std::time::Duration::from_secs(1)
+            );
+
+            loop {
+                interval.tick().await;
+                
+                let manager_guard = manager.read().await;
+                if let Err(e) = manager_guard.deliver_pending_events(EVENT_DELIVERY_BATCH).await {
+                    error!("Failed to deliver module events: {}", e);
+                }
+            }
+        });
+
+        if self.config.metering.enabled {
+            // Start resource usage sampling
+            let manager = self.manager.clone();
+            let sample_interval = self.config.metering.sample_interval_seconds;
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(
+                    std::time::Duration::from_secs(sample_interval)
+                );
+
+                loop {
+                    interval.tick().await;
+                    
+                    let manager_guard = manager.read().await;
+                    if let Err(e) = manager_guard.meter_usage(sample_interval as f64).await {
+                        error!("Failed to meter module usage: {}", e);
+                    }
+                }
+            });
+
+            // Start usage billing reports
+            let billing = self.billing.clone();
+            let report_interval = self.config.metering.billing_report_interval_seconds;
+            tokio::spawn(async move {
+                let mut interval = tokio::time::interval(
+                    std::time::Duration::from_secs(report_interval)
+                );
+
+                loop {
+                    interval.tick().await;
+                    
+                    if let Err(e) = billing.report_closed_hours().await {
+                        error!("Failed to report module usage for billing: {}", e);
+                    }
+                }
+            });
+        }
+
         // Start sandbox cleanup
         let sandbox = self.sandbox.clone();
         tokio::spawn(async move {
@@ -263,6 +413,24 @@ impl ModuleServiceRuntime {
         Ok(())
     }
 
+    async fn sync_revocations(
+        marketplace: &MarketplaceImpl,
+        repository: &PostgresModuleRepository,
+    ) -> ModuleResult<()> {
+        for revocation in marketplace.get_revocations().await? {
+            repository.save_revocation(&Self::normalize_revocation(revocation)).await?;
+        }
+        Ok(())
+    }
+
+    // Package revocations are matched against lowercase hex checksums
+    fn normalize_revocation(mut revocation: crate::Revocation) -> crate::Revocation {
+        if revocation.kind == crate::RevocationKind::Package {
+            revocation.value = revocation.value.to_lowercase();
+        }
+        revocation
+    }
+
     /// Handle module installation request
     pub async fn install_module(
         &self,
@@ -302,12 +470,20 @@ impl ModuleServiceRuntime {
         manager.get_module_status(instance_id).await
     }
 
-    /// Search marketplace modules
+    /// Search marketplace modules, with facets
     pub async fn search_marketplace(
         &self,
-        query: &crate::ModuleSearchQuery,
-    ) -> ModuleResult<crate::ModuleSearchResult> {
-        self.marketplace.search(query).await
+        query: &crate::MarketplaceSearchQuery,
+    ) -> ModuleResult<crate::MarketplaceSearchResult> {
+        self.search.search(query).await
+    }
+
+    /// Recommend marketplace modules to a tenant
+    pub async fn recommend_modules(
+        &self,
+        request: &crate::RecommendationRequest,
+    ) -> ModuleResult<Vec<crate::marketplace::ModuleRecommendation>> {
+        self.search.recommend(request).await
     }
 
     /// Get module from marketplace
@@ -382,9 +558,248 @@ impl ModuleServiceRuntime {
         manager.get_module_resource_usage(instance_id).await
     }
 
+    /// Get what a tenant admin consents to before installing or updating a
+    /// module; the latest version unless one is given
+    pub async fn get_consent_screen(
+        &self,
+        tenant_id: &str,
+        module_id: &str,
+        version: Option<String>,
+    ) -> ModuleResult<crate::ConsentScreen> {
+        let version = match version {
+            Some(version) => version,
+            None => self.marketplace.get_module(module_id).await?
+                .ok_or_else(|| ModuleError::NotFound(module_id.to_string()))?
+                .version
+                .to_string(),
+        };
+        let package = self.marketplace.download(module_id, &version).await?;
+
+        let manager = self.manager.read().await;
+        manager.consent_screen(tenant_id, &package).await
+    }
+
+    /// Get the capabilities a tenant granted a module
+    pub async fn get_capability_grant(
+        &self,
+        tenant_id: &str,
+        module_id: &str,
+    ) -> ModuleResult<Option<crate::CapabilityGrant>> {
+        let manager = self.manager.read().await;
+        manager.get_capability_grant(module_id, tenant_id).await
+    }
+
+    /// Register a developer's package signing key
+    pub async fn register_developer_key(
+        &self,
+        request: crate::RegisterDeveloperKeyRequest,
+    ) -> ModuleResult<crate::DeveloperKey> {
+        let key = crate::DeveloperKey {
+            key_id: request.key_id,
+            developer_id: request.developer_id,
+            public_key: request.public_key,
+            created_at: chrono::Utc::now(),
+        };
+        key.validate()?;
+
+        self.repository.save_developer_key(&key).await?;
+        info!("Registered developer key {} for {}", key.key_id, key.developer_id);
+        Ok(key)
+    }
+
+    /// Get a developer's package signing key
+    pub async fn get_developer_key(&self, key_id: &str) -> ModuleResult<crate::DeveloperKey> {
+        self.repository.get_developer_key(key_id).await?
+            .ok_or_else(|| ModuleError::NotFound(format!("Developer key {}", key_id)))
+    }
+
+    /// Add a signing key or package to the revocation list
+    pub async fn revoke(&self, request: crate::RevokeRequest) -> ModuleResult<crate::Revocation> {
+        if request.value.trim().is_empty() {
+            return Err(ModuleError::ValidationFailed("Revocation requires a value".to_string()));
+        }
+
+        let revocation = Self::normalize_revocation(crate::Revocation {
+            kind: request.kind,
+            value: request.value,
+            reason: request.reason,
+            revoked_at: chrono::Utc::now(),
+        });
+        self.repository.save_revocation(&revocation).await?;
+        info!("Revoked {} {}", revocation.kind.as_str(), revocation.value);
+        Ok(revocation)
+    }
+
+    /// List the revocation list
+    pub async fn list_revocations(&self) -> ModuleResult<Vec<crate::Revocation>> {
+        self.repository.list_revocations().await
+    }
+
+    /// Start a staged rollout of a module version across tenants
+    pub async fn start_rollout(&self, request: crate::StartRolloutRequest) -> ModuleResult<crate::ModuleRollout> {
+        self.rollouts.start(request).await
+    }
+
+    /// Get a rollout
+    pub async fn get_rollout(&self, rollout_id: Uuid) -> ModuleResult<crate::ModuleRollout> {
+        self.rollouts.get(rollout_id).await
+    }
+
+    /// List a module's rollouts
+    pub async fn list_module_rollouts(&self, module_id: &str) -> ModuleResult<Vec<crate::ModuleRollout>> {
+        self.rollouts.list(module_id).await
+    }
+
+    /// Pause a rollout after its current stage
+    pub async fn pause_rollout(&self, rollout_id: Uuid) -> ModuleResult<crate::ModuleRollout> {
+        self.rollouts.pause(rollout_id).await
+    }
+
+    /// Resume a paused rollout
+    pub async fn resume_rollout(&self, rollout_id: Uuid) -> ModuleResult<crate::ModuleRollout> {
+        self.rollouts.resume(rollout_id).await
+    }
+
+    /// Roll a paused rollout back
+    pub async fn rollback_rollout(&self, rollout_id: Uuid) -> ModuleResult<crate::ModuleRollout> {
+        self.rollouts.rollback(rollout_id).await
+    }
+
     /// Broadcast event to modules
     pub async fn broadcast_event(&self, event: crate::ModuleEvent) -> ModuleResult<()> {
         let manager = self.manager.read().await;
         manager.broadcast_event(event).await
     }
-}
\ No newline at end of file
+
+    /// Get the settings of a module instance with its schema
+    pub async fn get_module_settings(&self, instance_id: Uuid) -> ModuleResult<crate::ModuleSettingsView> {
+        let manager = self.manager.read().await;
+        manager.get_settings(instance_id).await
+    }
+
+    /// Change the settings of a module instance
+    pub async fn update_module_settings(
+        &self,
+        instance_id: Uuid,
+        request: crate::UpdateSettingsRequest,
+    ) -> ModuleResult<crate::ModuleSettings> {
+        let manager = self.manager.read().await;
+        manager.update_settings(instance_id, request).await
+    }
+
+    /// List the settings versions of a module instance
+    pub async fn list_module_settings_versions(&self, instance_id: Uuid) -> ModuleResult<Vec<crate::ModuleSettings>> {
+        let manager = self.manager.read().await;
+        manager.list_settings_versions(instance_id).await
+    }
+
+    /// Publish a platform event to the modules of a tenant subscribed to it
+    pub async fn publish_platform_event(
+        &self,
+        request: crate::PublishPlatformEventRequest,
+    ) -> ModuleResult<crate::BusEvent> {
+        self.events.publish_platform_event(&request.tenant_id, &request.event_type, request.data).await
+    }
+
+    /// List a tenant's dead-lettered event deliveries
+    pub async fn list_dead_letters(&self, tenant_id: &str) -> ModuleResult<Vec<crate::EventDelivery>> {
+        self.events.dead_letters(tenant_id).await
+    }
+
+    /// Retry a dead-lettered event delivery
+    pub async fn retry_dead_letter(&self, tenant_id: &str, delivery_id: Uuid) -> ModuleResult<()> {
+        self.events.retry_dead_letter(tenant_id, delivery_id).await
+    }
+
+    /// Get a tenant's metered module usage between two times
+    pub async fn tenant_usage(
+        &self,
+        tenant_id: &str,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+    ) -> ModuleResult<crate::TenantUsageReport> {
+        self.meter.tenant_usage(tenant_id, from, to).await
+    }
+
+    /// Get a frontend asset of a module version
+    pub async fn get_module_asset(&self, module_id: &str, version: &str, path: &str) -> ModuleResult<crate::ModuleAsset> {
+        self.assets.get_asset(module_id, version, path).await
+    }
+
+    /// Cache-Control header of served frontend assets
+    pub fn asset_cache_control(&self) -> String {
+        self.assets.cache_control()
+    }
+
+    /// Get the micro-frontends the tenant's shell mounts
+    pub async fn frontend_manifest(&self, tenant_id: &str) -> ModuleResult<crate::FrontendManifest> {
+        self.assets.frontend_manifest(tenant_id).await
+    }
+
+    /// Scan a marketplace module version ahead of installs, so reviewers
+    /// can work through it before tenants ask for it
+    pub async fn scan_marketplace_module(
+        &self,
+        module_id: &str,
+        version: &str,
+    ) -> ModuleResult<crate::SecurityReport> {
+        let package = self.marketplace.download(module_id, version).await?;
+        self.security_reviews.report_for(&package).await
+    }
+
+    /// Get a security scan report
+    pub async fn get_security_report(&self, scan_id: Uuid) -> ModuleResult<crate::SecurityReport> {
+        self.security_reviews.get(scan_id).await
+    }
+
+    /// List security reports by review status
+    pub async fn list_security_reports(
+        &self,
+        status: crate::ReviewStatus,
+    ) -> ModuleResult<Vec<crate::SecurityReport>> {
+        self.security_reviews.list(status).await
+    }
+
+    /// Approve or reject a package waiting for security review
+    pub async fn review_security_report(
+        &self,
+        scan_id: Uuid,
+        decision: crate::ReviewDecision,
+    ) -> ModuleResult<crate::SecurityReport> {
+        self.security_reviews.decide(scan_id, decision).await
+    }
+
+    /// Run a module's background job, as its workflow-service schedule
+    /// does on each tick
+    pub async fn run_module_job(&self, tenant_id: &str, job_id: Uuid) -> ModuleResult<crate::JobRun> {
+        let manager = self.manager.clone();
+        self.jobs.execute(tenant_id, job_id, |job, run_id| async move {
+            manager.read().await.dispatch_job(&job, run_id).await
+        }).await
+    }
+
+    /// List the background jobs of a tenant's modules
+    pub async fn list_module_jobs(&self, tenant_id: &str) -> ModuleResult<Vec<crate::ModuleJob>> {
+        self.jobs.list_tenant_jobs(tenant_id).await
+    }
+
+    /// List a job's recent runs, newest first
+    pub async fn list_module_job_runs(&self, tenant_id: &str, job_id: Uuid) -> ModuleResult<Vec<crate::JobRun>> {
+        self.jobs.list_runs(tenant_id, job_id).await
+    }
+
+    /// Stop a tenant's job from running until it is enabled again
+    pub async fn disable_module_job(
+        &self,
+        tenant_id: &str,
+        job_id: Uuid,
+        request: crate::DisableJobRequest,
+    ) -> ModuleResult<crate::ModuleJob> {
+        self.jobs.disable(tenant_id, job_id, request).await
+    }
+
+    /// Let a disabled job run again
+    pub async fn enable_module_job(&self, tenant_id: &str, job_id: Uuid) -> ModuleResult<crate::ModuleJob> {
+        self.jobs.enable(tenant_id, job_id).await
+    }
+}
@@ -1,33 +1,70 @@
-use serde::{Deserialize, Serialize};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
 use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 use async_trait::async_trait;
 use serde_json::Value;
 
 use crate::{
     ModuleResult, ModuleError, ModuleMetadata, ModuleManifest, AdxModule,
     ModuleStatus, HealthStatus, ResourceUsage, ModuleEvent, ExtensionPoint, ExtensionContext,
+    CapabilityGrants, ModuleHost,
 };
+use crate::events::event_type_matches;
+use crate::jobs::{JobSchedule, ModuleJob, RegisterJobRequest, JOBS_SCOPE};
 
-/// ADX Module SDK - Provides utilities and abstractions for module development
+pub mod cli;
+pub mod harness;
+
+/// ADX Module SDK - Provides utilities and abstractions for module development.
+/// Host calls are limited to the capabilities the tenant granted the module.
 pub struct ModuleSDK {
+    pub grants: Arc<CapabilityGrants>,
     pub logger: ModuleLogger,
     pub config: ModuleConfigManager,
     pub storage: ModuleStorage,
     pub http: ModuleHttpClient,
     pub events: ModuleEventBus,
+    pub jobs: ModuleJobs,
     pub ui: ModuleUIBuilder,
     pub workflows: ModuleWorkflowBuilder,
     pub database: ModuleDatabaseBuilder,
 }
 
 impl ModuleSDK {
-    pub fn new(module_id: String, tenant_id: String) -> Self {
+    pub fn new(module_id: String, tenant_id: String, grants: CapabilityGrants) -> Self {
+        let grants = Arc::new(grants);
+        let http = ModuleHttpClient::new(&module_id, grants.clone());
+        let events = ModuleEventBus::new(&module_id, grants.clone());
+        let jobs = ModuleJobs::new(&module_id, grants.clone());
+        Self::with_host_api(module_id, tenant_id, grants, http, events, jobs)
+    }
+
+    /// SDK of a module instance bound to its host
+    pub fn bind(module_id: String, host: ModuleHost) -> Self {
+        let grants = Arc::new(host.grants.clone());
+        let tenant_id = host.tenant_id.clone();
+        let http = ModuleHttpClient::bound(&module_id, grants.clone(), host.clone());
+        let events = ModuleEventBus::bound(&module_id, grants.clone(), host.clone());
+        let jobs = ModuleJobs::bound(&module_id, grants.clone(), host);
+        Self::with_host_api(module_id, tenant_id, grants, http, events, jobs)
+    }
+
+    fn with_host_api(
+        module_id: String,
+        tenant_id: String,
+        grants: Arc<CapabilityGrants>,
+        http: ModuleHttpClient,
+        events: ModuleEventBus,
+        jobs: ModuleJobs,
+    ) -> Self {
         Self {
+            grants: grants.clone(),
             logger: ModuleLogger::new(&module_id),
             config: ModuleConfigManager::new(&module_id, &tenant_id),
-            storage: ModuleStorage::new(&module_id, &tenant_id),
-            http: ModuleHttpClient::new(&module_id),
-            events: ModuleEventBus::new(&module_id),
+            storage: ModuleStorage::new(&module_id, &tenant_id, grants),
+            http,
+            events,
+            jobs,
             ui: ModuleUIBuilder::new(&module_id),
             workflows: ModuleWorkflowBuilder::new(&module_id),
             database: ModuleDatabaseBuilder::new(&module_id, &tenant_id),
@@ -47,7 +84,12 @@ pub struct BaseModule {
 
 impl BaseModule {
     pub fn new(metadata: ModuleMetadata, manifest: ModuleManifest) -> Self {
-        let sdk = ModuleSDK::new(metadata.id.clone(), "default".to_string());
+        // Nothing is granted until the host binds the module to a tenant
+        let sdk = ModuleSDK::new(
+            metadata.id.clone(),
+            "default".to_string(),
+            CapabilityGrants::new(&metadata.id, Default::default()),
+        );
         
         Self {
             metadata,
@@ -156,8 +198,13 @@ impl AdxModule for BaseModule {
 
     async fn handle_event(&mut self, event: ModuleEvent) -> ModuleResult<()> {
         self.sdk.logger.debug(&format!("Received event: {:?}", event));
-        // Override in derived modules for custom event handling
-        Ok(())
+        // Bus events and job runs go to the handlers the module registered;
+        // override in derived modules for other events
+        match event {
+            ModuleEvent::Custom { event_type, data } => self.sdk.events.dispatch(&event_type, data),
+            ModuleEvent::JobTriggered { job, payload, .. } => self.sdk.jobs.dispatch(&job, payload),
+            _ => Ok(()),
+        }
     }
 
     async fn execute_command(&mut self, command: String, args: Vec<String>) -> ModuleResult<Value> {
@@ -178,6 +225,10 @@ impl AdxModule for BaseModule {
         // This would need to be implemented differently due to ownership
         HashMap::new()
     }
+
+    fn bind_host(&mut self, host: ModuleHost) {
+        self.sdk = ModuleSDK::bind(self.metadata.id.clone(), host);
+    }
 }
 
 /// Module logging utilities
@@ -254,17 +305,20 @@ impl ModuleConfigManager {
 pub struct ModuleStorage {
     module_id: String,
     tenant_id: String,
+    grants: Arc<CapabilityGrants>,
 }
 
 impl ModuleStorage {
-    pub fn new(module_id: &str, tenant_id: &str) -> Self {
+    pub fn new(module_id: &str, tenant_id: &str, grants: Arc<CapabilityGrants>) -> Self {
         Self {
             module_id: module_id.to_string(),
             tenant_id: tenant_id.to_string(),
+            grants,
         }
     }
 
     pub async fn store(&self, key: &str, data: &[u8]) -> ModuleResult<()> {
+        self.grants.check_scope("storage:write")?;
         // Store data in module-specific storage
         let storage_key = format!("modules/{}/{}/{}", self.tenant_id, self.module_id, key);
         // Implementation would use actual storage backend
@@ -272,6 +326,7 @@ impl ModuleStorage {
     }
 
     pub async fn retrieve(&self, key: &str) -> ModuleResult<Option<Vec<u8>>> {
+        self.grants.check_scope("storage:read")?;
         // Retrieve data from module-specific storage
         let storage_key = format!("modules/{}/{}/{}", self.tenant_id, self.module_id, key);
         // Implementation would use actual storage backend
@@ -279,6 +334,7 @@ impl ModuleStorage {
     }
 
     pub async fn delete(&self, key: &str) -> ModuleResult<()> {
+        self.grants.check_scope("storage:write")?;
         // Delete data from module-specific storage
         let storage_key = format!("modules/{}/{}/{}", self.tenant_id, self.module_id, key);
         // Implementation would use actual storage backend
@@ -286,6 +342,7 @@ impl ModuleStorage {
     }
 
     pub async fn list_keys(&self, prefix: Option<&str>) -> ModuleResult<Vec<String>> {
+        self.grants.check_scope("storage:read")?;
         // List keys in module storage
         Ok(vec![])
     }
@@ -295,10 +352,12 @@ impl ModuleStorage {
 pub struct ModuleHttpClient {
     module_id: String,
     client: reqwest::Client,
+    grants: Arc<CapabilityGrants>,
+    host: Option<ModuleHost>,
 }
 
 impl ModuleHttpClient {
-    pub fn new(module_id: &str) -> Self {
+    pub fn new(module_id: &str, grants: Arc<CapabilityGrants>) -> Self {
         let client = reqwest::Client::builder()
             .user_agent(format!("ADX-Module/{}", module_id))
             .timeout(std::time::Duration::from_secs(30))
@@ -308,61 +367,235 @@ impl ModuleHttpClient {
         Self {
             module_id: module_id.to_string(),
             client,
+            grants,
+            host: None,
+        }
+    }
+
+    fn bound(module_id: &str, grants: Arc<CapabilityGrants>, host: ModuleHost) -> Self {
+        Self {
+            host: Some(host),
+            ..Self::new(module_id, grants)
         }
     }
 
     pub async fn get(&self, url: &str) -> ModuleResult<reqwest::Response> {
+        self.check_endpoint("GET", url)?;
         let response = self.client.get(url).send().await?;
         Ok(response)
     }
 
     pub async fn post(&self, url: &str, body: Value) -> ModuleResult<reqwest::Response> {
+        self.check_endpoint("POST", url)?;
         let response = self.client.post(url).json(&body).send().await?;
         Ok(response)
     }
 
     pub async fn put(&self, url: &str, body: Value) -> ModuleResult<reqwest::Response> {
+        self.check_endpoint("PUT", url)?;
         let response = self.client.put(url).json(&body).send().await?;
         Ok(response)
     }
 
     pub async fn delete(&self, url: &str) -> ModuleResult<reqwest::Response> {
+        self.check_endpoint("DELETE", url)?;
         let response = self.client.delete(url).send().await?;
         Ok(response)
     }
+
+    fn check_endpoint(&self, method: &str, url: &str) -> ModuleResult<()> {
+        let url = reqwest::Url::parse(url)
+            .map_err(|e| ModuleError::ValidationFailed(format!("Invalid URL {}: {}", url, e)))?;
+        self.grants.check_endpoint(method, url.path())?;
+
+        // Host API calls are metered for usage billing
+        if let Some(host) = &self.host {
+            host.meter.record_api_call(host.instance_id);
+        }
+        Ok(())
+    }
 }
 
-/// Module event bus for inter-module communication
+/// Handler of the events a module subscribed to
+pub type EventHandlerFn = Box<dyn Fn(Value) -> ModuleResult<()> + Send + Sync>;
+
+/// Module event bus for inter-module communication. A module publishes the
+/// events its manifest declares and subscribes, from `initialize`, to those
+/// its grant covers; handlers run when the host delivers a matching event.
 pub struct ModuleEventBus {
     module_id: String,
+    grants: Arc<CapabilityGrants>,
+    host: Option<ModuleHost>,
+    handlers: RwLock<Vec<(String, EventHandlerFn)>>,
 }
 
 impl ModuleEventBus {
-    pub fn new(module_id: &str) -> Self {
+    pub fn new(module_id: &str, grants: Arc<CapabilityGrants>) -> Self {
         Self {
             module_id: module_id.to_string(),
+            grants,
+            host: None,
+            handlers: RwLock::new(Vec::new()),
         }
     }
 
-    pub async fn emit(&self, event_type: &str, data: Value) -> ModuleResult<()> {
-        // Emit event to the global event bus
-        tracing::info!(
+    fn bound(module_id: &str, grants: Arc<CapabilityGrants>, host: ModuleHost) -> Self {
+        Self {
+            host: Some(host),
+            ..Self::new(module_id, grants)
+        }
+    }
+
+    /// Publish `{module_id}.{name}`; `data` must match the schema the
+    /// manifest declares for it
+    pub async fn emit(&self, name: &str, data: Value) -> ModuleResult<()> {
+        let host = self.host()?;
+        let event = host.events.publish(host.instance_id, &self.module_id, &host.tenant_id, name, data).await?;
+        tracing::debug!(
             module_id = %self.module_id,
-            event_type = %event_type,
-            "Emitting event"
+            event_type = %event.event_type,
+            event_id = %event.id,
+            "Published event"
         );
         Ok(())
     }
 
-    pub async fn subscribe(&self, event_type: &str, handler: Box<dyn Fn(Value) -> ModuleResult<()>>) -> ModuleResult<()> {
-        // Subscribe to events
+    pub async fn publish<T: Serialize>(&self, name: &str, event: &T) -> ModuleResult<()> {
+        self.emit(name, serde_json::to_value(event)?).await
+    }
+
+    /// Subscribe to an event type, or to a prefix with a trailing `*`
+    pub async fn subscribe(&self, event_type: &str, handler: EventHandlerFn) -> ModuleResult<()> {
+        self.grants.check_subscription(event_type)?;
+        let host = self.host()?;
+        host.events.subscribe(host.instance_id, &host.tenant_id, event_type).await?;
+
+        self.handlers.write()
+            .map_err(|_| ModuleError::InternalError("Event handlers lock poisoned".to_string()))?
+            .push((event_type.to_string(), handler));
         tracing::info!(
             module_id = %self.module_id,
             event_type = %event_type,
-            "Subscribing to event"
+            "Subscribed to event"
         );
         Ok(())
     }
+
+    pub async fn subscribe_typed<T, F>(&self, event_type: &str, handler: F) -> ModuleResult<()>
+    where
+        T: DeserializeOwned,
+        F: Fn(T) -> ModuleResult<()> + Send + Sync + 'static,
+    {
+        self.subscribe(event_type, Box::new(move |data| handler(serde_json::from_value(data)?))).await
+    }
+
+    /// Run the handlers subscribed to an event type. An error fails the
+    /// delivery and the host retries it.
+    pub fn dispatch(&self, event_type: &str, data: Value) -> ModuleResult<()> {
+        let handlers = self.handlers.read()
+            .map_err(|_| ModuleError::InternalError("Event handlers lock poisoned".to_string()))?;
+        for (pattern, handler) in handlers.iter() {
+            if event_type_matches(pattern, event_type) {
+                handler(data.clone())?;
+            }
+        }
+        Ok(())
+    }
+
+    fn host(&self) -> ModuleResult<&ModuleHost> {
+        self.host.as_ref().ok_or_else(|| ModuleError::RuntimeError(
+            format!("Module {} is not bound to a host", self.module_id)
+        ))
+    }
+}
+
+/// Handler of a background job's runs, given the job's payload
+pub type JobHandlerFn = Box<dyn Fn(Value) -> ModuleResult<()> + Send + Sync>;
+
+/// Background jobs of a module. A module registers its jobs, from
+/// `initialize`, with the `jobs:schedule` scope; the host schedules them
+/// and runs the handler on each run. A handler's error fails the run,
+/// which tenant admins see in the job's history.
+pub struct ModuleJobs {
+    module_id: String,
+    grants: Arc<CapabilityGrants>,
+    host: Option<ModuleHost>,
+    handlers: RwLock<HashMap<String, JobHandlerFn>>,
+}
+
+impl ModuleJobs {
+    pub fn new(module_id: &str, grants: Arc<CapabilityGrants>) -> Self {
+        Self {
+            module_id: module_id.to_string(),
+            grants,
+            host: None,
+            handlers: RwLock::new(HashMap::new()),
+        }
+    }
+
+    fn bound(module_id: &str, grants: Arc<CapabilityGrants>, host: ModuleHost) -> Self {
+        Self {
+            host: Some(host),
+            ..Self::new(module_id, grants)
+        }
+    }
+
+    /// Register a job run on `schedule`, or update it if the schedule or
+    /// payload changed
+    pub async fn register(
+        &self,
+        name: &str,
+        schedule: JobSchedule,
+        payload: Value,
+        handler: JobHandlerFn,
+    ) -> ModuleResult<ModuleJob> {
+        self.grants.check_scope(JOBS_SCOPE)?;
+        let host = self.host()?;
+        let job = host.jobs.register(host.instance_id, &self.module_id, &host.tenant_id, RegisterJobRequest {
+            name: name.to_string(),
+            schedule,
+            payload,
+            timeout_seconds: None,
+        }).await?;
+
+        self.handlers.write()
+            .map_err(|_| ModuleError::InternalError("Job handlers lock poisoned".to_string()))?
+            .insert(name.to_string(), handler);
+        tracing::info!(module_id = %self.module_id, job = %name, "Registered job");
+        Ok(job)
+    }
+
+    /// Run `handler` every time `cron` matches, in UTC
+    pub async fn every(&self, name: &str, cron: &str, handler: JobHandlerFn) -> ModuleResult<ModuleJob> {
+        let schedule = JobSchedule::Recurring { cron: cron.to_string(), timezone: None };
+        self.register(name, schedule, Value::Null, handler).await
+    }
+
+    /// Remove a job; runs already started finish
+    pub async fn cancel(&self, name: &str) -> ModuleResult<()> {
+        let host = self.host()?;
+        host.jobs.cancel(host.instance_id, name).await?;
+        self.handlers.write()
+            .map_err(|_| ModuleError::InternalError("Job handlers lock poisoned".to_string()))?
+            .remove(name);
+        Ok(())
+    }
+
+    /// Run the handler of a job the host triggered
+    pub fn dispatch(&self, name: &str, payload: Value) -> ModuleResult<()> {
+        let handlers = self.handlers.read()
+            .map_err(|_| ModuleError::InternalError("Job handlers lock poisoned".to_string()))?;
+        let handler = handlers.get(name).ok_or_else(|| ModuleError::RuntimeError(
+            format!("Module {} has no handler for job {}", self.module_id, name)
+        ))?;
+        handler(payload)
+    }
+
+    fn host(&self) -> ModuleResult<&ModuleHost> {
+        self.host.as_ref().ok_or_else(|| ModuleError::RuntimeError(
+            format!("Module {} is not bound to a host", self.module_id)
+        ))
+    }
 }
 
 /// Module UI builder for creating frontend components
@@ -660,7 +893,7 @@ macro_rules! adx_module {
                 self.base.configure(config).await
             }
 
-            async fn status(&self) -> ModuleResult<crate::traits::ModuleStatus> {
+            async fn status(&self) -> ModuleResult<$crate::traits::ModuleStatus> {
                 self.base.status().await
             }
 
@@ -687,6 +920,10 @@ macro_rules! adx_module {
             fn get_extension_points(&self) -> std::collections::HashMap<String, Box<dyn ExtensionPoint>> {
                 self.base.get_extension_points()
             }
+
+            fn bind_host(&mut self, host: $crate::ModuleHost) {
+                self.base.bind_host(host)
+            }
         }
     };
 }
@@ -749,6 +986,8 @@ pub mod example {
                     },
                 },
                 permissions: vec![],
+                required_capabilities: crate::CapabilityManifest::default(),
+                published_events: vec![],
                 resources: crate::ResourceRequirements {
                     min_memory_mb: 64,
                     max_memory_mb: 256,
@@ -772,6 +1011,8 @@ pub mod example {
                     migration_entry: Some("./lib/migrations.js".to_string()),
                     test_entry: Some("./lib/tests.js".to_string()),
                 },
+                micro_frontends: vec![],
+                lifecycle_hooks: Default::default(),
                 sandbox_config: crate::SandboxConfiguration {
                     isolation_level: crate::IsolationLevel::Process,
                     allowed_syscalls: vec![],
@@ -883,6 +1124,10 @@ pub mod example {
         fn get_extension_points(&self) -> HashMap<String, Box<dyn ExtensionPoint>> {
             self.base.get_extension_points()
         }
+
+        fn bind_host(&mut self, host: ModuleHost) {
+            self.base.bind_host(host)
+        }
     }
 }
 
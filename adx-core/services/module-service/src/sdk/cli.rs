@@ -0,0 +1,629 @@
+use std::path::{Path, PathBuf};
+use chrono::Utc;
+use jsonschema::JSONSchema;
+use semver::{Version, VersionReq};
+use serde_json::Value;
+use walkdir::WalkDir;
+
+use crate::{
+    ModuleResult, ModuleError, ModuleManifest, ModuleMetadata, ModulePackage, ModuleAuthor,
+    ModuleCategory, VersionRequirement, AdxModule, EventBus, PackageSigner, IsolationLevel,
+    PackageAsset, LockedDependency,
+};
+use crate::loader::ModuleLoaderRegistry;
+use crate::marketplace::{MarketplaceConfig, ModuleMarketplace, ModuleSubmission, SubmissionResult};
+use crate::signing::package_checksum;
+use crate::assets::{content_type_for, remote_entry_path};
+
+/// File a module's manifest is read from, at the root of its directory
+pub const MANIFEST_FILE: &str = "manifest.json";
+
+/// Directory `package` writes packages to
+pub const DIST_DIR: &str = "dist";
+
+/// Extension of packaged modules
+pub const PACKAGE_EXTENSION: &str = "adxpkg";
+
+/// What `adx-module new` generates
+#[derive(Debug, Clone)]
+pub struct ScaffoldOptions {
+    pub module_id: String,
+    pub name: String,
+    pub author: String,
+    /// Directory the module is created in; must not exist yet
+    pub directory: PathBuf,
+    /// Path of the module-service crate the module builds against
+    pub sdk_path: String,
+}
+
+/// Problems `validate_manifest` found. Errors stop packaging; warnings are
+/// what marketplace review is likely to flag.
+#[derive(Debug, Clone, Default)]
+pub struct ManifestReport {
+    pub errors: Vec<String>,
+    pub warnings: Vec<String>,
+}
+
+impl ManifestReport {
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+/// Create a new Rust module that builds, loads and passes its harness test
+/// as generated
+pub fn scaffold(options: &ScaffoldOptions) -> ModuleResult<PathBuf> {
+    check_module_id(&options.module_id)?;
+    if options.directory.exists() {
+        return Err(ModuleError::AlreadyExists(
+            format!("{} already exists", options.directory.display())
+        ));
+    }
+
+    let crate_name = options.module_id.replace('-', "_");
+    let type_name = type_name(&options.module_id);
+    let manifest = scaffold_manifest(options, &crate_name);
+    let render = |template: &str| {
+        template
+            .replace("{{module_id}}", &options.module_id)
+            .replace("{{name}}", &options.name)
+            .replace("{{crate_name}}", &crate_name)
+            .replace("{{type_name}}", &type_name)
+            .replace("{{sdk_path}}", &options.sdk_path)
+    };
+
+    std::fs::create_dir_all(options.directory.join("src"))?;
+    std::fs::create_dir_all(options.directory.join("tests"))?;
+    std::fs::write(options.directory.join(MANIFEST_FILE), serde_json::to_string_pretty(&manifest)?)?;
+    std::fs::write(options.directory.join("Cargo.toml"), render(CARGO_TEMPLATE))?;
+    std::fs::write(options.directory.join("src/lib.rs"), render(LIB_TEMPLATE))?;
+    std::fs::write(options.directory.join("tests/module.rs"), render(TEST_TEMPLATE))?;
+    std::fs::write(options.directory.join("README.md"), render(README_TEMPLATE))?;
+    std::fs::write(options.directory.join(".gitignore"), "/target\n/dist\n")?;
+
+    Ok(options.directory.clone())
+}
+
+/// Read the manifest of the module in `directory`
+pub fn load_manifest(directory: &Path) -> ModuleResult<ModuleManifest> {
+    let path = directory.join(MANIFEST_FILE);
+    let manifest = std::fs::read_to_string(&path)
+        .map_err(|e| ModuleError::IoError(format!("Failed to read {}: {}", path.display(), e)))?;
+    serde_json::from_str(&manifest)
+        .map_err(|e| ModuleError::ValidationFailed(format!("{} is not a valid manifest: {}", path.display(), e)))
+}
+
+/// Run the checks module-service makes on install, and those marketplace
+/// review makes, without installing anything
+pub fn validate_manifest(manifest: &ModuleManifest) -> ManifestReport {
+    let mut report = ManifestReport::default();
+    let metadata = &manifest.metadata;
+
+    if let Err(e) = check_module_id(&metadata.id) {
+        report.errors.push(e.to_string());
+    }
+    if metadata.name.trim().is_empty() {
+        report.errors.push("metadata.name is empty".to_string());
+    }
+    if metadata.description.trim().is_empty() {
+        report.warnings.push("metadata.description is empty".to_string());
+    }
+    if metadata.author.email.is_none() {
+        report.warnings.push("metadata.author.email is not set; reviewers cannot contact you".to_string());
+    }
+
+    for dependency in &manifest.dependencies {
+        if let Err(e) = VersionReq::parse(&dependency.version_requirement) {
+            report.errors.push(format!(
+                "Dependency {} has an invalid version requirement {}: {}",
+                dependency.module_id, dependency.version_requirement, e
+            ));
+        }
+    }
+
+    if let Err(e) = manifest.required_capabilities.validate() {
+        report.errors.push(e.to_string());
+    }
+    if let Err(e) = EventBus::compile_schemas(&metadata.id, &manifest.published_events) {
+        report.errors.push(e.to_string());
+    }
+    check_configuration(manifest, &mut report);
+
+    let resources = &manifest.resources;
+    if resources.min_memory_mb > resources.max_memory_mb {
+        report.errors.push("resources.min_memory_mb is above resources.max_memory_mb".to_string());
+    }
+    if resources.min_cpu_cores > resources.max_cpu_cores {
+        report.errors.push("resources.min_cpu_cores is above resources.max_cpu_cores".to_string());
+    }
+    if manifest.sandbox_config.resource_limits.max_memory_mb < resources.min_memory_mb {
+        report.errors.push("sandbox_config.resource_limits.max_memory_mb is below resources.min_memory_mb".to_string());
+    }
+    if matches!(manifest.sandbox_config.isolation_level, IsolationLevel::None) {
+        report.warnings.push("sandbox_config.isolation_level is None; marketplace modules must be isolated".to_string());
+    }
+
+    match &manifest.extension_points.backend_entry {
+        None => report.errors.push("extension_points.backend_entry is not set".to_string()),
+        Some(entry) if !BACKEND_EXTENSIONS.iter().any(|extension| entry.ends_with(extension)) => {
+            report.warnings.push(format!("No loader recognizes backend entry {}; it will load as JavaScript", entry));
+        }
+        Some(_) => {}
+    }
+
+    if !manifest.micro_frontends.is_empty() && remote_entry_path(manifest).is_none() {
+        report.errors.push("micro_frontends are declared but extension_points.frontend_entry is not set".to_string());
+    }
+    check_lifecycle_hooks(manifest, &mut report);
+
+    report
+}
+
+fn check_lifecycle_hooks(manifest: &ModuleManifest, report: &mut ManifestReport) {
+    let hooks = &manifest.lifecycle_hooks;
+    for (phase, hook) in [("install", &hooks.install), ("upgrade", &hooks.upgrade), ("uninstall", &hooks.uninstall)] {
+        let Some(hook) = hook else {
+            continue;
+        };
+        if hook.run.trim().is_empty() {
+            report.errors.push(format!("lifecycle_hooks.{}.run is empty", phase));
+        }
+        if hook.timeout_seconds == Some(0) {
+            report.errors.push(format!("lifecycle_hooks.{}.timeout_seconds is 0", phase));
+        }
+        if hook.compensate.is_none() {
+            report.warnings.push(format!(
+                "lifecycle_hooks.{} has no compensate; a failed {} cannot undo it", phase, phase
+            ));
+        }
+    }
+}
+
+/// Package the module in `directory`, signed when a signer is given, into
+/// `dist/{id}-{version}.adxpkg`. The backend entry, and the frontend entry
+/// if there is one, must have been built.
+pub fn package(directory: &Path, signer: Option<&PackageSigner>) -> ModuleResult<(ModulePackage, PathBuf)> {
+    let mut package = local_package(directory)?;
+    if let Some(signer) = signer {
+        signer.sign(&mut package);
+    }
+
+    let dist = directory.join(DIST_DIR);
+    std::fs::create_dir_all(&dist)?;
+    let path = dist.join(format!("{}-{}.{}", package.metadata.id, package.metadata.version, PACKAGE_EXTENSION));
+    std::fs::write(&path, serde_json::to_vec(&package)?)?;
+
+    Ok((package, path))
+}
+
+/// An unsigned package of the module in `directory`, after validating its
+/// manifest
+pub fn local_package(directory: &Path) -> ModuleResult<ModulePackage> {
+    let manifest = load_manifest(directory)?;
+    let report = validate_manifest(&manifest);
+    if !report.is_valid() {
+        return Err(ModuleError::ValidationFailed(
+            format!("Invalid manifest: {}", report.errors.join("; "))
+        ));
+    }
+
+    // Checked by validate_manifest
+    let entry = manifest.extension_points.backend_entry.clone().unwrap_or_default();
+    let entry_path = directory.join(&entry);
+    let content = std::fs::read(&entry_path)
+        .map_err(|e| ModuleError::IoError(format!("Failed to read backend entry {}: {}", entry_path.display(), e)))?;
+
+    let assets = frontend_assets(directory, &manifest)?;
+    let locked_dependencies = locked_dependencies(directory)?;
+
+    Ok(ModulePackage {
+        metadata: manifest.metadata.clone(),
+        checksum: package_checksum(&content),
+        size_bytes: content.len() as u64,
+        manifest,
+        content,
+        signature: None,
+        assets,
+        locked_dependencies,
+    })
+}
+
+/// Every file in the frontend entry's directory, which is where the
+/// frontend build writes its remote entry and chunks
+fn frontend_assets(directory: &Path, manifest: &ModuleManifest) -> ModuleResult<Vec<PackageAsset>> {
+    let Some(entry) = &manifest.extension_points.frontend_entry else {
+        return Ok(vec![]);
+    };
+    let entry_path = directory.join(entry);
+    if !entry_path.is_file() {
+        return Err(ModuleError::IoError(format!("Frontend entry {} has not been built", entry_path.display())));
+    }
+    let root = match entry_path.parent() {
+        Some(root) if root != directory => root,
+        _ => return Err(ModuleError::ValidationFailed(
+            format!("Frontend entry {} must be in the frontend build's output directory, not the module root", entry)
+        )),
+    };
+
+    let mut assets = Vec::new();
+    for file in WalkDir::new(root).sort_by_file_name() {
+        let file = file.map_err(|e| ModuleError::IoError(format!("Failed to read {}: {}", root.display(), e)))?;
+        if !file.file_type().is_file() {
+            continue;
+        }
+        let path = file.path().strip_prefix(root)
+            .map_err(|e| ModuleError::InternalError(e.to_string()))?
+            .components()
+            .map(|component| component.as_os_str().to_string_lossy())
+            .collect::<Vec<_>>()
+            .join("/");
+        assets.push(PackageAsset {
+            content_type: content_type_for(&path).to_string(),
+            content: std::fs::read(file.path())?,
+            path,
+        });
+    }
+    Ok(assets)
+}
+
+/// Registry libraries locked in the module's Cargo.lock and package-lock.json
+fn locked_dependencies(directory: &Path) -> ModuleResult<Vec<LockedDependency>> {
+    let mut dependencies = Vec::new();
+
+    let cargo_lock = directory.join("Cargo.lock");
+    if cargo_lock.is_file() {
+        let lock: toml::Value = toml::from_str(&std::fs::read_to_string(&cargo_lock)?)
+            .map_err(|e| ModuleError::ValidationFailed(format!("Invalid {}: {}", cargo_lock.display(), e)))?;
+        let packages = lock.get("package").and_then(|packages| packages.as_array()).cloned().unwrap_or_default();
+        for package in packages {
+            // Path and workspace crates, the module's own among them, have no source
+            let from_registry = package.get("source")
+                .and_then(|source| source.as_str())
+                .is_some_and(|source| source.starts_with("registry+"));
+            if let (true, Some(name), Some(version)) = (
+                from_registry,
+                package.get("name").and_then(|name| name.as_str()),
+                package.get("version").and_then(|version| version.as_str()),
+            ) {
+                dependencies.push(LockedDependency {
+                    ecosystem: "crates.io".to_string(),
+                    name: name.to_string(),
+                    version: version.to_string(),
+                });
+            }
+        }
+    }
+
+    let npm_lock = directory.join("package-lock.json");
+    if npm_lock.is_file() {
+        let lock: Value = serde_json::from_str(&std::fs::read_to_string(&npm_lock)?)
+            .map_err(|e| ModuleError::ValidationFailed(format!("Invalid {}: {}", npm_lock.display(), e)))?;
+        let packages = lock.get("packages").and_then(|packages| packages.as_object()).cloned().unwrap_or_default();
+        for (path, package) in packages {
+            // "" is the module's own package; linked packages are local
+            let Some((_, name)) = path.rsplit_once("node_modules/") else {
+                continue;
+            };
+            if package.get("link").and_then(|link| link.as_bool()).unwrap_or(false) {
+                continue;
+            }
+            if let Some(version) = package.get("version").and_then(|version| version.as_str()) {
+                dependencies.push(LockedDependency {
+                    ecosystem: "npm".to_string(),
+                    name: name.to_string(),
+                    version: version.to_string(),
+                });
+            }
+        }
+    }
+
+    dependencies.sort_by(|a, b| (&a.ecosystem, &a.name, &a.version).cmp(&(&b.ecosystem, &b.name, &b.version)));
+    dependencies.dedup();
+    Ok(dependencies)
+}
+
+/// Load the module in `directory` the way module-service does
+pub async fn load_local(directory: &Path) -> ModuleResult<Box<dyn AdxModule>> {
+    let package = local_package(directory)?;
+    ModuleLoaderRegistry::new().load_module(&package).await
+}
+
+pub fn read_package(path: &Path) -> ModuleResult<ModulePackage> {
+    let package = std::fs::read(path)
+        .map_err(|e| ModuleError::IoError(format!("Failed to read {}: {}", path.display(), e)))?;
+    serde_json::from_slice(&package)
+        .map_err(|e| ModuleError::ValidationFailed(format!("{} is not a module package: {}", path.display(), e)))
+}
+
+/// Submit a package for marketplace review
+pub async fn publish(
+    package: &ModulePackage,
+    documentation: String,
+    marketplace_url: &str,
+    api_key: &str,
+) -> ModuleResult<SubmissionResult> {
+    if package_checksum(&package.content) != package.checksum {
+        return Err(ModuleError::ValidationFailed(
+            format!("Checksum of {} {} does not match its contents", package.metadata.id, package.metadata.version)
+        ));
+    }
+    if package.signature.is_none() {
+        return Err(ModuleError::ValidationFailed(
+            "The marketplace only accepts signed packages; package with --key".to_string()
+        ));
+    }
+
+    let marketplace = ModuleMarketplace::new(MarketplaceConfig {
+        base_url: marketplace_url.trim_end_matches('/').to_string(),
+        api_key: api_key.to_string(),
+        timeout_seconds: 300,
+        cache_ttl_seconds: 0,
+        enable_analytics: false,
+        enable_recommendations: false,
+        payment_providers: vec![],
+    });
+
+    marketplace.submit_module(ModuleSubmission {
+        metadata: package.metadata.clone(),
+        package_data: serde_json::to_vec(package)?,
+        documentation,
+        screenshots: vec![],
+        demo_url: None,
+    }).await
+}
+
+const BACKEND_EXTENSIONS: [&str; 6] = [".so", ".dll", ".dylib", ".js", ".py", ".wasm"];
+
+fn check_module_id(module_id: &str) -> ModuleResult<()> {
+    let valid = !module_id.is_empty()
+        && module_id.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+        && module_id.starts_with(|c: char| c.is_ascii_lowercase())
+        && !module_id.ends_with('-');
+    if !valid {
+        return Err(ModuleError::ValidationFailed(format!(
+            "Invalid module id {}: use lowercase letters, digits and dashes, starting with a letter",
+            module_id
+        )));
+    }
+    if module_id == "platform" {
+        return Err(ModuleError::ValidationFailed("The module id platform is reserved".to_string()));
+    }
+    Ok(())
+}
+
+fn check_configuration(manifest: &ModuleManifest, report: &mut ManifestReport) {
+    let configuration = &manifest.configuration;
+    if configuration.config_schema.is_null() {
+        if !configuration.tenant_configurable.is_empty() {
+            report.warnings.push("Tenant-configurable settings have no configuration.config_schema".to_string());
+        }
+        return;
+    }
+
+    let schema = match JSONSchema::compile(&configuration.config_schema) {
+        Ok(schema) => schema,
+        Err(e) => {
+            report.errors.push(format!("configuration.config_schema is invalid: {}", e));
+            return;
+        }
+    };
+    if let Err(errors) = schema.validate(&configuration.default_config) {
+        let errors: Vec<String> = errors.map(|error| error.to_string()).collect();
+        report.errors.push(format!("configuration.default_config does not match the schema: {}", errors.join("; ")));
+    }
+
+    let properties = configuration.config_schema.get("properties").and_then(Value::as_object);
+    for key in configuration.tenant_configurable.iter().chain(&configuration.required_config) {
+        if !properties.map_or(false, |properties| properties.contains_key(key)) {
+            report.warnings.push(format!("Setting {} is not described in configuration.config_schema", key));
+        }
+    }
+}
+
+/// `invoice-sync` -> `InvoiceSyncModule`
+fn type_name(module_id: &str) -> String {
+    let mut name: String = module_id.split('-')
+        .filter(|part| !part.is_empty())
+        .map(|part| {
+            let mut chars = part.chars();
+            chars.next().map(|first| first.to_ascii_uppercase().to_string() + chars.as_str()).unwrap_or_default()
+        })
+        .collect();
+    if !name.ends_with("Module") {
+        name.push_str("Module");
+    }
+    name
+}
+
+fn scaffold_manifest(options: &ScaffoldOptions, crate_name: &str) -> ModuleManifest {
+    let now = Utc::now();
+    let metadata = ModuleMetadata {
+        id: options.module_id.clone(),
+        name: options.name.clone(),
+        version: Version::new(0, 1, 0),
+        description: format!("{} module for ADX Core", options.name),
+        long_description: None,
+        author: ModuleAuthor {
+            name: options.author.clone(),
+            email: None,
+            website: None,
+            organization: None,
+        },
+        license: "MIT".to_string(),
+        homepage: None,
+        repository: None,
+        documentation: None,
+        keywords: vec![],
+        categories: vec![ModuleCategory::Utility],
+        adx_core_version: VersionRequirement {
+            min_version: Version::new(2, 0, 0),
+            max_version: None,
+            compatible_versions: vec![],
+        },
+        created_at: now,
+        updated_at: now,
+    };
+
+    ModuleManifest {
+        metadata,
+        dependencies: vec![],
+        capabilities: crate::ModuleCapabilities {
+            ui_extensions: vec![],
+            api_extensions: vec![],
+            workflow_extensions: vec![],
+            database_extensions: vec![],
+            event_handlers: vec![],
+            cross_platform_features: crate::CrossPlatformFeatures {
+                web_support: true,
+                desktop_support: vec![],
+                mobile_support: vec![],
+                native_integrations: vec![],
+            },
+        },
+        permissions: vec![],
+        required_capabilities: crate::CapabilityManifest {
+            scopes: vec!["storage:read".to_string(), "storage:write".to_string()],
+            api_endpoints: vec![],
+            event_subscriptions: vec!["platform.user.logged_in".to_string()],
+        },
+        published_events: vec![crate::EventDeclaration {
+            name: "greeted".to_string(),
+            description: Some("A user was greeted".to_string()),
+            schema: serde_json::json!({
+                "type": "object",
+                "properties": {"user_id": {"type": "string"}},
+                "required": ["user_id"]
+            }),
+        }],
+        resources: crate::ResourceRequirements {
+            min_memory_mb: 32,
+            max_memory_mb: 128,
+            min_cpu_cores: 0.1,
+            max_cpu_cores: 0.5,
+            storage_mb: 50,
+            network_bandwidth_mbps: None,
+            concurrent_operations: 10,
+        },
+        configuration: crate::ModuleConfiguration {
+            config_schema: serde_json::json!({
+                "type": "object",
+                "properties": {"greeting": {"type": "string", "minLength": 1}}
+            }),
+            default_config: serde_json::json!({"greeting": "Hello"}),
+            required_config: vec![],
+            tenant_configurable: vec!["greeting".to_string()],
+            user_configurable: vec![],
+        },
+        extension_points: crate::ExtensionPoints {
+            backend_entry: Some(format!("target/release/lib{}.so", crate_name)),
+            frontend_entry: None,
+            workflow_entry: None,
+            migration_entry: None,
+            test_entry: Some("tests/module.rs".to_string()),
+        },
+        micro_frontends: vec![],
+        lifecycle_hooks: Default::default(),
+        sandbox_config: crate::SandboxConfiguration {
+            isolation_level: IsolationLevel::Process,
+            allowed_syscalls: vec![],
+            blocked_syscalls: vec![],
+            network_restrictions: crate::NetworkRestrictions {
+                allowed_domains: vec![],
+                blocked_domains: vec![],
+                allowed_ports: vec![],
+                blocked_ports: vec![],
+                max_connections: 10,
+            },
+            file_system_restrictions: crate::FileSystemRestrictions {
+                allowed_paths: vec!["/tmp".to_string()],
+                blocked_paths: vec![],
+                read_only_paths: vec![],
+                max_file_size: 10 * 1024 * 1024,
+                max_files: 100,
+            },
+            resource_limits: crate::ResourceLimits {
+                max_memory_mb: 128,
+                max_cpu_percent: 50.0,
+                max_execution_time_seconds: 300,
+                max_disk_io_mbps: 50,
+                max_network_io_mbps: 10,
+            },
+        },
+    }
+}
+
+const CARGO_TEMPLATE: &str = r#"[package]
+name = "{{module_id}}"
+version = "0.1.0"
+edition = "2021"
+
+[lib]
+crate-type = ["cdylib", "rlib"]
+
+[dependencies]
+module-service = { path = "{{sdk_path}}" }
+async-trait = "0.1"
+serde_json = "1.0"
+
+[dev-dependencies]
+tokio = { version = "1.0", features = ["macros", "rt-multi-thread"] }
+"#;
+
+const LIB_TEMPLATE: &str = r#"use module_service::sdk::BaseModule;
+use module_service::{
+    adx_module, AdxModule, ExtensionPoint, HealthStatus, ModuleEvent, ModuleManifest,
+    ModuleMetadata, ModuleResult, ResourceUsage,
+};
+
+fn manifest() -> ModuleManifest {
+    serde_json::from_str(include_str!("../manifest.json")).expect("manifest.json is a valid manifest")
+}
+
+// Override the lifecycle methods of the generated module by implementing
+// AdxModule by hand; see the SDK's example module.
+adx_module!({{type_name}}, manifest().metadata, manifest());
+
+/// Entry point module-service calls when it loads the module
+#[no_mangle]
+#[allow(improper_ctypes_definitions)]
+pub extern "C" fn create_module() -> *mut dyn AdxModule {
+    Box::into_raw(Box::new({{type_name}}::new()))
+}
+"#;
+
+const TEST_TEMPLATE: &str = r#"use module_service::sdk::harness::ModuleTestHarness;
+use serde_json::json;
+use {{crate_name}}::{{type_name}};
+
+#[tokio::test]
+async fn starts_healthy() {
+    let mut harness = ModuleTestHarness::new(Box::new({{type_name}}::new())).unwrap();
+    harness.start(json!({})).await.unwrap();
+
+    assert!(harness.health().await.unwrap().is_healthy);
+    harness.stop().await.unwrap();
+}
+
+#[tokio::test]
+async fn rejects_invalid_settings() {
+    let mut harness = ModuleTestHarness::new(Box::new({{type_name}}::new())).unwrap();
+
+    assert!(harness.start(json!({"greeting": ""})).await.is_err());
+}
+"#;
+
+const README_TEMPLATE: &str = r#"# {{name}}
+
+An ADX Core module.
+
+## Development
+
+```bash
+cargo test                       # run the module against the mock host
+cargo build --release
+adx-module validate              # check manifest.json
+adx-module run                   # run the module in a local sandbox
+adx-module package --key dev.pk8 --key-id {{module_id}}-dev
+adx-module publish dist/{{module_id}}-0.1.0.adxpkg
+```
+"#;
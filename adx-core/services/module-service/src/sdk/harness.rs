@@ -0,0 +1,677 @@
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde_json::Value;
+use uuid::Uuid;
+
+use crate::{
+    ModuleResult, ModuleError, ModuleManifest, ModuleMetadata, ModuleInstance, ModuleRepository,
+    ModuleSearchQuery, ModuleSearchResult, AdxModule, ModuleEvent, ModuleHost, HealthStatus,
+    CapabilityGrant, CapabilityGrants, DeveloperKey, Revocation, RevocationKind,
+    EventBus, EventBusConfig, BusEvent, EventDelivery, DeliveryStatus, UsageMeter, UsageTotals,
+    ModuleSettings, ModuleStatus, ResourceUsage,
+};
+use crate::rollout::{ModuleRollout, RolloutStatus};
+use crate::metering::ModuleUsage;
+use crate::assets::{FrontendBundle, ModuleAsset};
+use crate::search::{IndexHit, MarketplaceListing};
+use crate::security::{ReviewStatus, SecurityReport};
+use crate::jobs::{JobConfig, JobRun, JobRunStatus, ModuleJob, ModuleJobScheduler};
+use crate::settings::{merge_settings, validate_settings};
+
+/// Tenant the harness runs modules in
+pub const HARNESS_TENANT: &str = "local-tenant";
+
+/// Runs a module against a mock of the host API, without a database or a
+/// running module-service. The module gets every capability its manifest
+/// asks for, its events go through a real event bus and its usage is
+/// metered as in production, all in memory. Jobs the module registers are
+/// kept but never scheduled; tests run them with `run_job`.
+///
+/// ```ignore
+/// let mut harness = ModuleTestHarness::new(Box::new(MyModule::new()))?;
+/// harness.start(json!({"api_url": "https://example.com"})).await?;
+/// harness.publish_platform_event("platform.user.logged_in", json!({"user_id": "u1"})).await?;
+/// assert_eq!(harness.emitted_events().await?.len(), 1);
+/// ```
+pub struct ModuleTestHarness {
+    module: Box<dyn AdxModule>,
+    manifest: ModuleManifest,
+    host: ModuleHost,
+    repository: Arc<MockHostRepository>,
+    /// Subscribed to everything the module publishes, so tests can see it
+    observer_id: Uuid,
+    started_at: DateTime<Utc>,
+}
+
+impl ModuleTestHarness {
+    pub fn new(module: Box<dyn AdxModule>) -> ModuleResult<Self> {
+        Self::with_event_config(module, EventBusConfig::default())
+    }
+
+    pub fn with_event_config(mut module: Box<dyn AdxModule>, config: EventBusConfig) -> ModuleResult<Self> {
+        let manifest = module.manifest().clone();
+        manifest.required_capabilities.validate()?;
+
+        let repository = Arc::new(MockHostRepository::default());
+        let host = ModuleHost {
+            instance_id: Uuid::new_v4(),
+            tenant_id: HARNESS_TENANT.to_string(),
+            grants: CapabilityGrants::new(&manifest.metadata.id, manifest.required_capabilities.clone()),
+            events: Arc::new(EventBus::new(config, repository.clone())),
+            meter: Arc::new(UsageMeter::new(repository.clone())),
+            jobs: Arc::new(ModuleJobScheduler::local(JobConfig::default(), repository.clone())),
+        };
+        module.bind_host(host.clone());
+
+        Ok(Self {
+            module,
+            manifest,
+            host,
+            repository,
+            observer_id: Uuid::new_v4(),
+            started_at: Utc::now(),
+        })
+    }
+
+    /// Initialize and start the module with `config` over the manifest's
+    /// defaults, validated as on install
+    pub async fn start(&mut self, config: Value) -> ModuleResult<()> {
+        let schemas = EventBus::compile_schemas(&self.manifest.metadata.id, &self.manifest.published_events)?;
+        self.host.events.register_instance(self.host.instance_id, schemas).await;
+        self.host.events
+            .subscribe(self.observer_id, HARNESS_TENANT, &format!("{}.*", self.manifest.metadata.id))
+            .await?;
+
+        let config = merge_settings(&self.manifest.configuration.default_config, &config);
+        validate_settings(&self.manifest.configuration, &config)?;
+
+        self.save_instance(ModuleStatus::Installed, config.clone()).await?;
+        self.module.initialize(config.clone()).await?;
+        self.module.start().await?;
+        self.save_instance(ModuleStatus::Active, config).await
+    }
+
+    /// Stop and shut the module down
+    pub async fn stop(&mut self) -> ModuleResult<()> {
+        self.module.stop().await?;
+        self.module.shutdown().await?;
+        self.host.events.release_instance(self.host.instance_id).await;
+        if let Some(mut instance) = self.repository.get_instance(self.host.instance_id).await? {
+            instance.status = ModuleStatus::Inactive;
+            self.repository.save_instance(&instance).await?;
+        }
+        Ok(())
+    }
+
+    /// Reconfigure the module, as when a tenant changes its settings
+    pub async fn configure(&mut self, changes: Value) -> ModuleResult<()> {
+        let config = merge_settings(&self.manifest.configuration.default_config, &changes);
+        validate_settings(&self.manifest.configuration, &config)?;
+        self.module.configure(config).await
+    }
+
+    pub async fn execute_command(&mut self, command: &str, args: Vec<String>) -> ModuleResult<Value> {
+        self.module.execute_command(command.to_string(), args).await
+    }
+
+    pub async fn health(&self) -> ModuleResult<HealthStatus> {
+        self.module.health().await
+    }
+
+    /// Publish a platform event and deliver it to the module if it
+    /// subscribed
+    pub async fn publish_platform_event(&mut self, event_type: &str, data: Value) -> ModuleResult<BusEvent> {
+        let event = self.host.events.publish_platform_event(HARNESS_TENANT, event_type, data).await?;
+        self.deliver_events().await?;
+        Ok(event)
+    }
+
+    /// Deliver an event of another module of the tenant straight to the
+    /// module's handlers
+    pub async fn deliver_module_event(&mut self, event_type: &str, data: Value) -> ModuleResult<()> {
+        self.module.handle_event(ModuleEvent::Custom {
+            event_type: event_type.to_string(),
+            data,
+        }).await
+    }
+
+    /// Hand due deliveries to the module; failed ones are retried as by the
+    /// host. Returns how many the module handled.
+    pub async fn deliver_events(&mut self) -> ModuleResult<usize> {
+        let mut handled = 0;
+        for delivery in self.host.events.due_deliveries(u32::MAX).await? {
+            if delivery.instance_id != self.host.instance_id {
+                continue;
+            }
+            let result = self.module.handle_event(ModuleEvent::Custom {
+                event_type: delivery.event.event_type.clone(),
+                data: delivery.event.data.clone(),
+            }).await;
+
+            match result {
+                Ok(()) => {
+                    self.host.events.delivered(delivery.id).await?;
+                    handled += 1;
+                }
+                Err(e) => self.host.events.delivery_failed(&delivery, &e.to_string()).await?,
+            }
+        }
+        Ok(handled)
+    }
+
+    /// Run one of the jobs the module registered now, as its schedule would
+    pub async fn run_job(&mut self, name: &str) -> ModuleResult<JobRun> {
+        let job = self.host.jobs.get_by_name(self.host.instance_id, name).await?;
+        let module = &mut self.module;
+        self.host.jobs.execute(HARNESS_TENANT, job.id, |job, run_id| async move {
+            module.handle_event(ModuleEvent::JobTriggered {
+                job: job.name,
+                run_id,
+                payload: job.payload,
+            }).await
+        }).await
+    }
+
+    /// Jobs the module registered
+    pub async fn jobs(&self) -> ModuleResult<Vec<ModuleJob>> {
+        self.host.jobs.list_instance_jobs(self.host.instance_id).await
+    }
+
+    /// Events the module published, oldest first
+    pub async fn emitted_events(&self) -> ModuleResult<Vec<BusEvent>> {
+        Ok(self.repository.deliveries_to(self.observer_id)?
+            .into_iter()
+            .map(|delivery| delivery.event)
+            .collect())
+    }
+
+    /// Deliveries the module failed too often, as an operator would see them
+    pub async fn dead_letters(&self) -> ModuleResult<Vec<EventDelivery>> {
+        self.host.events.dead_letters(HARNESS_TENANT).await
+    }
+
+    /// Meter the module's resource usage over `seconds`, and return its usage
+    /// so far, including host API calls
+    pub async fn usage(&self, seconds: f64) -> ModuleResult<UsageTotals> {
+        let usage = self.module.resource_usage().await?;
+        self.host.meter
+            .record_sample(self.host.instance_id, &self.manifest.metadata.id, HARNESS_TENANT, &usage, seconds)
+            .await?;
+
+        let report = self.host.meter
+            .tenant_usage(HARNESS_TENANT, self.started_at, Utc::now() + chrono::Duration::hours(1))
+            .await?;
+        Ok(report.totals)
+    }
+
+    pub fn module(&self) -> &dyn AdxModule {
+        self.module.as_ref()
+    }
+
+    pub fn module_mut(&mut self) -> &mut dyn AdxModule {
+        self.module.as_mut()
+    }
+
+    pub fn host(&self) -> &ModuleHost {
+        &self.host
+    }
+
+    /// Record the module as a tenant's instance, which jobs need to run
+    async fn save_instance(&self, status: ModuleStatus, configuration: Value) -> ModuleResult<()> {
+        let now = Utc::now();
+        self.repository.save_instance(&ModuleInstance {
+            id: self.host.instance_id,
+            module_id: self.manifest.metadata.id.clone(),
+            tenant_id: HARNESS_TENANT.to_string(),
+            version: self.manifest.metadata.version.clone(),
+            status,
+            configuration,
+            installation_path: String::new(),
+            installed_at: self.started_at,
+            activated_at: Some(now),
+            last_updated: now,
+            resource_usage: ResourceUsage {
+                memory_mb: 0,
+                cpu_percent: 0.0,
+                disk_mb: 0,
+                network_in_mbps: 0.0,
+                network_out_mbps: 0.0,
+                active_connections: 0,
+                last_measured: now,
+            },
+            health_status: HealthStatus {
+                is_healthy: true,
+                last_health_check: now,
+                error_count: 0,
+                warning_count: 0,
+                uptime_seconds: 0,
+                response_time_ms: 0,
+            },
+        }).await
+    }
+}
+
+/// In-memory repository backing the mock host. Keeps what the host API
+/// needs: instances, grants, settings, event deliveries, usage, assets and
+/// jobs.
+#[derive(Default)]
+pub struct MockHostRepository {
+    metadata: Mutex<HashMap<String, ModuleMetadata>>,
+    instances: Mutex<HashMap<Uuid, ModuleInstance>>,
+    grants: Mutex<HashMap<(String, String), CapabilityGrant>>,
+    developer_keys: Mutex<HashMap<String, DeveloperKey>>,
+    revocations: Mutex<Vec<Revocation>>,
+    deliveries: Mutex<Vec<EventDelivery>>,
+    settings: Mutex<Vec<ModuleSettings>>,
+    usage: Mutex<Vec<(ModuleUsage, bool)>>,
+    assets: Mutex<Vec<ModuleAsset>>,
+    bundles: Mutex<Vec<FrontendBundle>>,
+    jobs: Mutex<HashMap<Uuid, ModuleJob>>,
+    job_runs: Mutex<Vec<JobRun>>,
+}
+
+impl MockHostRepository {
+    fn deliveries_to(&self, instance_id: Uuid) -> ModuleResult<Vec<EventDelivery>> {
+        let mut deliveries: Vec<EventDelivery> = lock(&self.deliveries)?.iter()
+            .filter(|delivery| delivery.instance_id == instance_id)
+            .cloned()
+            .collect();
+        deliveries.sort_by_key(|delivery| delivery.event.published_at);
+        Ok(deliveries)
+    }
+}
+
+fn lock<T>(mutex: &Mutex<T>) -> ModuleResult<std::sync::MutexGuard<'_, T>> {
+    mutex.lock().map_err(|_| ModuleError::InternalError("Test harness state lock poisoned".to_string()))
+}
+
+fn not_hosted(what: &str) -> ModuleError {
+    ModuleError::InternalError(format!("The test harness does not support {}", what))
+}
+
+#[async_trait]
+impl ModuleRepository for MockHostRepository {
+    async fn save_metadata(&self, metadata: &ModuleMetadata) -> ModuleResult<()> {
+        lock(&self.metadata)?.insert(metadata.id.clone(), metadata.clone());
+        Ok(())
+    }
+
+    async fn get_metadata(&self, module_id: &str) -> ModuleResult<Option<ModuleMetadata>> {
+        Ok(lock(&self.metadata)?.get(module_id).cloned())
+    }
+
+    async fn list_modules(&self) -> ModuleResult<Vec<ModuleMetadata>> {
+        Ok(lock(&self.metadata)?.values().cloned().collect())
+    }
+
+    async fn search_modules(&self, _query: &ModuleSearchQuery) -> ModuleResult<ModuleSearchResult> {
+        Err(not_hosted("module search"))
+    }
+
+    async fn save_instance(&self, instance: &ModuleInstance) -> ModuleResult<()> {
+        lock(&self.instances)?.insert(instance.id, instance.clone());
+        Ok(())
+    }
+
+    async fn get_instance(&self, instance_id: Uuid) -> ModuleResult<Option<ModuleInstance>> {
+        Ok(lock(&self.instances)?.get(&instance_id).cloned())
+    }
+
+    async fn list_tenant_instances(&self, tenant_id: &str) -> ModuleResult<Vec<ModuleInstance>> {
+        Ok(lock(&self.instances)?.values().filter(|i| i.tenant_id == tenant_id).cloned().collect())
+    }
+
+    async fn list_module_instances(&self, module_id: &str) -> ModuleResult<Vec<ModuleInstance>> {
+        Ok(lock(&self.instances)?.values().filter(|i| i.module_id == module_id).cloned().collect())
+    }
+
+    async fn update_instance_status(&self, instance_id: Uuid, _status: crate::traits::ModuleStatus) -> ModuleResult<()> {
+        if let Some(instance) = lock(&self.instances)?.get_mut(&instance_id) {
+            instance.last_updated = Utc::now();
+        }
+        Ok(())
+    }
+
+    async fn delete_instance(&self, instance_id: Uuid) -> ModuleResult<()> {
+        lock(&self.instances)?.remove(&instance_id);
+        Ok(())
+    }
+
+    async fn save_capability_grant(&self, grant: &CapabilityGrant) -> ModuleResult<()> {
+        lock(&self.grants)?.insert((grant.module_id.clone(), grant.tenant_id.clone()), grant.clone());
+        Ok(())
+    }
+
+    async fn get_capability_grant(&self, module_id: &str, tenant_id: &str) -> ModuleResult<Option<CapabilityGrant>> {
+        Ok(lock(&self.grants)?.get(&(module_id.to_string(), tenant_id.to_string())).cloned())
+    }
+
+    async fn delete_capability_grant(&self, module_id: &str, tenant_id: &str) -> ModuleResult<()> {
+        lock(&self.grants)?.remove(&(module_id.to_string(), tenant_id.to_string()));
+        Ok(())
+    }
+
+    async fn save_developer_key(&self, key: &DeveloperKey) -> ModuleResult<()> {
+        lock(&self.developer_keys)?.insert(key.key_id.clone(), key.clone());
+        Ok(())
+    }
+
+    async fn get_developer_key(&self, key_id: &str) -> ModuleResult<Option<DeveloperKey>> {
+        Ok(lock(&self.developer_keys)?.get(key_id).cloned())
+    }
+
+    async fn save_revocation(&self, revocation: &Revocation) -> ModuleResult<()> {
+        lock(&self.revocations)?.push(revocation.clone());
+        Ok(())
+    }
+
+    async fn list_revocations(&self) -> ModuleResult<Vec<Revocation>> {
+        Ok(lock(&self.revocations)?.clone())
+    }
+
+    async fn is_revoked(&self, kind: RevocationKind, value: &str) -> ModuleResult<bool> {
+        Ok(lock(&self.revocations)?.iter()
+            .any(|revocation| revocation.kind.as_str() == kind.as_str() && revocation.value == value))
+    }
+
+    async fn create_rollout(&self, _rollout: &ModuleRollout) -> ModuleResult<()> {
+        Err(not_hosted("rollouts"))
+    }
+
+    async fn get_rollout(&self, _rollout_id: Uuid) -> ModuleResult<Option<ModuleRollout>> {
+        Err(not_hosted("rollouts"))
+    }
+
+    async fn list_module_rollouts(&self, _module_id: &str) -> ModuleResult<Vec<ModuleRollout>> {
+        Err(not_hosted("rollouts"))
+    }
+
+    async fn list_rollouts_in_progress(&self) -> ModuleResult<Vec<ModuleRollout>> {
+        Err(not_hosted("rollouts"))
+    }
+
+    async fn save_rollout_progress(&self, _rollout: &ModuleRollout) -> ModuleResult<()> {
+        Err(not_hosted("rollouts"))
+    }
+
+    async fn transition_rollout(
+        &self,
+        _rollout_id: Uuid,
+        _from: RolloutStatus,
+        _to: RolloutStatus,
+        _reason: Option<&str>,
+    ) -> ModuleResult<bool> {
+        Err(not_hosted("rollouts"))
+    }
+
+    async fn save_event_deliveries(&self, deliveries: &[EventDelivery]) -> ModuleResult<()> {
+        lock(&self.deliveries)?.extend(deliveries.iter().cloned());
+        Ok(())
+    }
+
+    async fn due_event_deliveries(&self, limit: u32) -> ModuleResult<Vec<EventDelivery>> {
+        let now = Utc::now();
+        let mut due: Vec<EventDelivery> = lock(&self.deliveries)?.iter()
+            .filter(|d| d.status == DeliveryStatus::Pending && d.next_attempt_at <= now)
+            .cloned()
+            .collect();
+        due.sort_by_key(|delivery| delivery.next_attempt_at);
+        due.truncate(limit as usize);
+        Ok(due)
+    }
+
+    async fn mark_event_delivered(&self, delivery_id: Uuid) -> ModuleResult<()> {
+        lock(&self.deliveries)?.retain(|delivery| delivery.id != delivery_id);
+        Ok(())
+    }
+
+    async fn mark_event_delivery_failed(
+        &self,
+        delivery_id: Uuid,
+        attempts: u32,
+        error: &str,
+        next_attempt_at: Option<DateTime<Utc>>,
+    ) -> ModuleResult<()> {
+        if let Some(delivery) = lock(&self.deliveries)?.iter_mut().find(|d| d.id == delivery_id) {
+            delivery.attempts = attempts;
+            delivery.last_error = Some(error.to_string());
+            match next_attempt_at {
+                Some(next_attempt_at) => delivery.next_attempt_at = next_attempt_at,
+                None => delivery.status = DeliveryStatus::DeadLettered,
+            }
+        }
+        Ok(())
+    }
+
+    async fn list_dead_lettered_deliveries(&self, tenant_id: &str) -> ModuleResult<Vec<EventDelivery>> {
+        Ok(lock(&self.deliveries)?.iter()
+            .filter(|d| d.status == DeliveryStatus::DeadLettered && d.event.tenant_id == tenant_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn requeue_event_delivery(&self, tenant_id: &str, delivery_id: Uuid) -> ModuleResult<bool> {
+        let mut deliveries = lock(&self.deliveries)?;
+        let delivery = deliveries.iter_mut().find(|d| {
+            d.id == delivery_id && d.event.tenant_id == tenant_id && d.status == DeliveryStatus::DeadLettered
+        });
+        Ok(match delivery {
+            Some(delivery) => {
+                delivery.status = DeliveryStatus::Pending;
+                delivery.attempts = 0;
+                delivery.next_attempt_at = Utc::now();
+                true
+            }
+            None => false,
+        })
+    }
+
+    async fn save_settings_version(&self, settings: &ModuleSettings) -> ModuleResult<bool> {
+        let mut versions = lock(&self.settings)?;
+        if versions.iter().any(|s| s.instance_id == settings.instance_id && s.version == settings.version) {
+            return Ok(false);
+        }
+        versions.push(settings.clone());
+        if let Some(instance) = lock(&self.instances)?.get_mut(&settings.instance_id) {
+            instance.configuration = settings.values.clone();
+        }
+        Ok(true)
+    }
+
+    async fn get_latest_settings(&self, instance_id: Uuid) -> ModuleResult<Option<ModuleSettings>> {
+        Ok(lock(&self.settings)?.iter()
+            .filter(|settings| settings.instance_id == instance_id)
+            .max_by_key(|settings| settings.version)
+            .cloned())
+    }
+
+    async fn list_settings_versions(&self, instance_id: Uuid) -> ModuleResult<Vec<ModuleSettings>> {
+        let mut versions: Vec<ModuleSettings> = lock(&self.settings)?.iter()
+            .filter(|settings| settings.instance_id == instance_id)
+            .cloned()
+            .collect();
+        versions.sort_by(|a, b| b.version.cmp(&a.version));
+        Ok(versions)
+    }
+
+    async fn add_module_usage(&self, usage: &ModuleUsage) -> ModuleResult<()> {
+        let mut hours = lock(&self.usage)?;
+        let hour = hours.iter_mut()
+            .find(|(hour, _)| hour.instance_id == usage.instance_id && hour.period_start == usage.period_start);
+        match hour {
+            Some((hour, _)) => {
+                hour.cpu_seconds += usage.cpu_seconds;
+                hour.memory_mb_seconds += usage.memory_mb_seconds;
+                hour.storage_mb_seconds += usage.storage_mb_seconds;
+                hour.api_calls += usage.api_calls;
+            }
+            None => hours.push((usage.clone(), false)),
+        }
+        Ok(())
+    }
+
+    async fn list_tenant_module_usage(
+        &self,
+        tenant_id: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> ModuleResult<Vec<ModuleUsage>> {
+        Ok(lock(&self.usage)?.iter()
+            .map(|(hour, _)| hour)
+            .filter(|hour| hour.tenant_id == tenant_id && hour.period_start >= from && hour.period_start < to)
+            .cloned()
+            .collect())
+    }
+
+    async fn list_unreported_module_usage(&self, before: DateTime<Utc>) -> ModuleResult<Vec<ModuleUsage>> {
+        Ok(lock(&self.usage)?.iter()
+            .filter(|(hour, reported)| !reported && hour.period_start < before)
+            .map(|(hour, _)| hour.clone())
+            .collect())
+    }
+
+    async fn mark_module_usage_reported(&self, instance_id: Uuid, period_start: DateTime<Utc>) -> ModuleResult<()> {
+        for (hour, reported) in lock(&self.usage)?.iter_mut() {
+            if hour.instance_id == instance_id && hour.period_start == period_start {
+                *reported = true;
+            }
+        }
+        Ok(())
+    }
+
+    async fn save_module_assets(&self, bundle: Option<&FrontendBundle>, assets: &[ModuleAsset]) -> ModuleResult<()> {
+        let mut stored = lock(&self.assets)?;
+        for asset in assets {
+            let exists = stored.iter()
+                .any(|a| a.module_id == asset.module_id && a.version == asset.version && a.path == asset.path);
+            if !exists {
+                stored.push(asset.clone());
+            }
+        }
+        if let Some(bundle) = bundle {
+            let mut bundles = lock(&self.bundles)?;
+            if !bundles.iter().any(|b| b.module_id == bundle.module_id && b.version == bundle.version) {
+                bundles.push(bundle.clone());
+            }
+        }
+        Ok(())
+    }
+
+    async fn get_module_asset(&self, module_id: &str, version: &semver::Version, path: &str) -> ModuleResult<Option<ModuleAsset>> {
+        Ok(lock(&self.assets)?.iter()
+            .find(|asset| asset.module_id == module_id && &asset.version == version && asset.path == path)
+            .cloned())
+    }
+
+    async fn get_frontend_bundle(&self, module_id: &str, version: &semver::Version) -> ModuleResult<Option<FrontendBundle>> {
+        Ok(lock(&self.bundles)?.iter()
+            .find(|bundle| bundle.module_id == module_id && &bundle.version == version)
+            .cloned())
+    }
+
+    async fn upsert_marketplace_listings(&self, _listings: &[MarketplaceListing]) -> ModuleResult<()> {
+        Err(not_hosted("marketplace search"))
+    }
+
+    async fn delete_marketplace_listings(&self, _module_ids: &[String]) -> ModuleResult<()> {
+        Err(not_hosted("marketplace search"))
+    }
+
+    async fn search_marketplace_index(&self, _text: Option<&str>, _limit: u32) -> ModuleResult<Vec<IndexHit>> {
+        Err(not_hosted("marketplace search"))
+    }
+
+    async fn list_co_installed_modules(&self, _module_ids: &[String], _exclude_tenant: &str) -> ModuleResult<Vec<(String, u64)>> {
+        Err(not_hosted("marketplace search"))
+    }
+
+    async fn save_security_report(&self, _report: &SecurityReport) -> ModuleResult<()> {
+        Err(not_hosted("security review"))
+    }
+
+    async fn get_security_report(&self, _scan_id: Uuid) -> ModuleResult<Option<SecurityReport>> {
+        Err(not_hosted("security review"))
+    }
+
+    async fn find_security_report(&self, _module_id: &str, _version: &semver::Version, _checksum: &str) -> ModuleResult<Option<SecurityReport>> {
+        Err(not_hosted("security review"))
+    }
+
+    async fn list_security_reports(&self, _status: ReviewStatus) -> ModuleResult<Vec<SecurityReport>> {
+        Err(not_hosted("security review"))
+    }
+
+    async fn list_module_security_reports(&self, _module_ids: &[String]) -> ModuleResult<Vec<SecurityReport>> {
+        Err(not_hosted("security review"))
+    }
+
+    async fn save_module_job(&self, job: &ModuleJob) -> ModuleResult<()> {
+        lock(&self.jobs)?.insert(job.id, job.clone());
+        Ok(())
+    }
+
+    async fn get_module_job(&self, job_id: Uuid) -> ModuleResult<Option<ModuleJob>> {
+        Ok(lock(&self.jobs)?.get(&job_id).cloned())
+    }
+
+    async fn find_module_job(&self, instance_id: Uuid, name: &str) -> ModuleResult<Option<ModuleJob>> {
+        Ok(lock(&self.jobs)?.values().find(|job| job.instance_id == instance_id && job.name == name).cloned())
+    }
+
+    async fn list_instance_jobs(&self, instance_id: Uuid) -> ModuleResult<Vec<ModuleJob>> {
+        let mut jobs: Vec<ModuleJob> = lock(&self.jobs)?.values()
+            .filter(|job| job.instance_id == instance_id)
+            .cloned()
+            .collect();
+        jobs.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(jobs)
+    }
+
+    async fn list_tenant_jobs(&self, tenant_id: &str) -> ModuleResult<Vec<ModuleJob>> {
+        let mut jobs: Vec<ModuleJob> = lock(&self.jobs)?.values()
+            .filter(|job| job.tenant_id == tenant_id)
+            .cloned()
+            .collect();
+        jobs.sort_by(|a, b| (&a.module_id, &a.name).cmp(&(&b.module_id, &b.name)));
+        Ok(jobs)
+    }
+
+    async fn delete_module_job(&self, job_id: Uuid) -> ModuleResult<()> {
+        lock(&self.jobs)?.remove(&job_id);
+        lock(&self.job_runs)?.retain(|run| run.job_id != job_id);
+        Ok(())
+    }
+
+    async fn save_job_run(&self, run: &JobRun) -> ModuleResult<()> {
+        let mut runs = lock(&self.job_runs)?;
+        match runs.iter_mut().find(|r| r.id == run.id) {
+            Some(existing) => *existing = run.clone(),
+            None => runs.push(run.clone()),
+        }
+        Ok(())
+    }
+
+    async fn list_job_runs(&self, job_id: Uuid, limit: u32) -> ModuleResult<Vec<JobRun>> {
+        let mut runs: Vec<JobRun> = lock(&self.job_runs)?.iter()
+            .filter(|run| run.job_id == job_id)
+            .cloned()
+            .collect();
+        runs.sort_by(|a, b| b.started_at.cmp(&a.started_at));
+        runs.truncate(limit as usize);
+        Ok(runs)
+    }
+
+    async fn count_running_job_runs(&self, instance_id: Uuid, since: DateTime<Utc>) -> ModuleResult<u32> {
+        Ok(lock(&self.job_runs)?.iter()
+            .filter(|run| run.instance_id == instance_id && run.status == JobRunStatus::Running && run.started_at > since)
+            .count() as u32)
+    }
+
+    async fn prune_job_runs(&self, job_id: Uuid, keep: u32) -> ModuleResult<()> {
+        let keep: Vec<Uuid> = self.list_job_runs(job_id, keep).await?.into_iter().map(|run| run.id).collect();
+        lock(&self.job_runs)?.retain(|run| run.job_id != job_id || keep.contains(&run.id));
+        Ok(())
+    }
+}
@@ -0,0 +1,509 @@
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use chrono::{DateTime, Utc};
+use semver::Version;
+use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
+use tracing::info;
+
+use crate::{ModuleResult, ModuleMetadata, ModuleCategory, ModuleRepository, SortBy, VersionRequirement};
+use crate::marketplace::{ModuleMarketplace, ModuleRecommendation, PricingModel};
+use crate::security::{ReviewStatus, SecurityReport};
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarketplaceSearchConfig {
+    pub enabled: bool,
+    /// How often listings changed in the marketplace catalog are indexed
+    pub sync_interval_seconds: u64,
+    pub sync_page_size: u32,
+    /// ADX Core version compatibility is checked against
+    pub platform_version: String,
+    /// Most listings a text query ranks and facets
+    pub max_candidates: u32,
+}
+
+impl Default for MarketplaceSearchConfig {
+    fn default() -> Self {
+        Self {
+            enabled: true,
+            sync_interval_seconds: 900,
+            sync_page_size: 200,
+            platform_version: "2.0.0".to_string(),
+            max_candidates: 5000,
+        }
+    }
+}
+
+/// A module as listed in the marketplace catalog
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketplaceListing {
+    pub metadata: ModuleMetadata,
+    pub pricing_model: PricingModel,
+    pub price: f64,
+    pub currency: String,
+    pub rating_average: f64,
+    pub review_count: u32,
+    pub install_count: u64,
+    /// Set on catalog changes that remove the module from the marketplace
+    #[serde(default)]
+    pub delisted: bool,
+}
+
+/// A listing matching a text query, with its full-text rank
+#[derive(Debug, Clone)]
+pub struct IndexHit {
+    pub listing: MarketplaceListing,
+    pub text_rank: f64,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct MarketplaceSearchQuery {
+    pub query: Option<String>,
+    pub categories: Vec<ModuleCategory>,
+    pub pricing_models: Vec<PricingModel>,
+    pub min_rating: Option<f64>,
+    /// Only modules compatible with the platform version
+    pub compatible_only: bool,
+    /// Defaults to the version the service runs
+    pub platform_version: Option<Version>,
+    pub author: Option<String>,
+    pub keywords: Vec<String>,
+    /// Defaults to relevance
+    pub sort_by: Option<SortBy>,
+    pub limit: u32,
+    pub offset: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketplaceSearchResult {
+    pub modules: Vec<MarketplaceSearchHit>,
+    pub total_count: u64,
+    pub has_more: bool,
+    pub facets: MarketplaceFacets,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MarketplaceSearchHit {
+    #[serde(flatten)]
+    pub listing: MarketplaceListing,
+    pub score: f64,
+    pub compatible: bool,
+    /// Security scan of the listed version, once it has been scanned here
+    pub security: Option<ListingSecurity>,
+}
+
+/// What marketplace search shows of a listed version's security report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListingSecurity {
+    pub scan_id: uuid::Uuid,
+    pub score: u8,
+    pub review_status: ReviewStatus,
+    pub critical_issues: u32,
+    pub high_issues: u32,
+    pub scanned_at: DateTime<Utc>,
+}
+
+impl From<&SecurityReport> for ListingSecurity {
+    fn from(report: &SecurityReport) -> Self {
+        Self {
+            scan_id: report.scan_id,
+            score: report.score,
+            review_status: report.review_status,
+            critical_issues: report.count(crate::Severity::Critical),
+            high_issues: report.count(crate::Severity::High),
+            scanned_at: report.scanned_at,
+        }
+    }
+}
+
+/// Counts per facet value. Each facet counts the listings matching every
+/// other filter, so selecting a value does not hide its alternatives.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct MarketplaceFacets {
+    pub categories: HashMap<String, u64>,
+    pub pricing: HashMap<String, u64>,
+    /// Listings rated at least 1, 2, 3 and 4, keyed "1+" to "4+"
+    pub ratings: HashMap<String, u64>,
+    /// "compatible" and "incompatible"
+    pub compatibility: HashMap<String, u64>,
+}
+
+/// What recommendations are tailored to, besides the installed modules
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TenantProfile {
+    pub industry: Option<String>,
+    pub company_size: Option<String>,
+    pub interests: Vec<ModuleCategory>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecommendationRequest {
+    pub tenant_id: String,
+    #[serde(default)]
+    pub profile: TenantProfile,
+    #[serde(default = "default_recommendation_limit")]
+    pub limit: u32,
+}
+
+fn default_recommendation_limit() -> u32 {
+    10
+}
+
+const DEFAULT_PAGE_SIZE: u32 = 20;
+const MAX_PAGE_SIZE: u32 = 100;
+
+// Ratings are pulled towards this average until a module has enough reviews
+const PRIOR_RATING: f64 = 3.5;
+const PRIOR_REVIEWS: f64 = 10.0;
+
+/// Full-text and faceted search over the marketplace catalog, indexed in
+/// Postgres, and recommendations from what tenants install together
+pub struct MarketplaceIndex {
+    config: MarketplaceSearchConfig,
+    platform_version: Version,
+    repository: Arc<dyn ModuleRepository>,
+    marketplace: Arc<ModuleMarketplace>,
+    last_sync: RwLock<Option<DateTime<Utc>>>,
+}
+
+impl MarketplaceIndex {
+    pub fn new(
+        config: MarketplaceSearchConfig,
+        repository: Arc<dyn ModuleRepository>,
+        marketplace: Arc<ModuleMarketplace>,
+    ) -> ModuleResult<Self> {
+        let platform_version = Version::parse(&config.platform_version)
+            .map_err(|e| crate::ModuleError::ConfigurationError(
+                format!("Invalid platform version {}: {}", config.platform_version, e)
+            ))?;
+
+        Ok(Self {
+            config,
+            platform_version,
+            repository,
+            marketplace,
+            last_sync: RwLock::new(None),
+        })
+    }
+
+    /// Index the catalog listings changed since the last sync; everything
+    /// on the first
+    pub async fn sync(&self) -> ModuleResult<usize> {
+        let started_at = Utc::now();
+        let since = *self.last_sync.read().await;
+
+        let mut offset = 0;
+        let mut indexed = 0;
+        loop {
+            let page = self.marketplace.get_catalog(since, offset, self.config.sync_page_size).await?;
+            let (delisted, listed): (Vec<_>, Vec<_>) = page.listings.into_iter()
+                .partition(|listing| listing.delisted);
+
+            self.repository.upsert_marketplace_listings(&listed).await?;
+            let delisted: Vec<String> = delisted.into_iter().map(|listing| listing.metadata.id).collect();
+            self.repository.delete_marketplace_listings(&delisted).await?;
+
+            indexed += listed.len() + delisted.len();
+            offset += self.config.sync_page_size;
+            if !page.has_more {
+                break;
+            }
+        }
+
+        *self.last_sync.write().await = Some(started_at);
+        if indexed > 0 {
+            info!("Indexed {} marketplace catalog changes", indexed);
+        }
+        Ok(indexed)
+    }
+
+    pub async fn search(&self, query: &MarketplaceSearchQuery) -> ModuleResult<MarketplaceSearchResult> {
+        let text = query.query.as_deref().map(str::trim).filter(|text| !text.is_empty());
+        let hits = self.repository.search_marketplace_index(text, self.config.max_candidates).await?;
+        let platform_version = query.platform_version.as_ref().unwrap_or(&self.platform_version);
+
+        let filters = Filters::new(query, platform_version);
+        let facets = filters.facets(&hits);
+
+        let max_rank = hits.iter().map(|hit| hit.text_rank).fold(0.0, f64::max);
+        let max_installs = hits.iter().map(|hit| hit.listing.install_count).max().unwrap_or(0);
+        let mut matches: Vec<MarketplaceSearchHit> = hits.into_iter()
+            .filter(|hit| filters.matches(&hit.listing, None))
+            .map(|hit| MarketplaceSearchHit {
+                score: relevance(&hit, max_rank, max_installs),
+                compatible: is_compatible(&hit.listing.metadata.adx_core_version, platform_version),
+                security: None,
+                listing: hit.listing,
+            })
+            .collect();
+        sort_hits(&mut matches, query.sort_by.as_ref().unwrap_or(&SortBy::Relevance));
+
+        let limit = match query.limit {
+            0 => DEFAULT_PAGE_SIZE,
+            limit => limit.min(MAX_PAGE_SIZE),
+        };
+        let total_count = matches.len() as u64;
+        let mut modules: Vec<MarketplaceSearchHit> = matches.into_iter()
+            .skip(query.offset as usize)
+            .take(limit as usize)
+            .collect();
+        self.attach_security_reports(&mut modules).await?;
+
+        Ok(MarketplaceSearchResult {
+            has_more: (query.offset as u64 + modules.len() as u64) < total_count,
+            modules,
+            total_count,
+            facets,
+        })
+    }
+
+    async fn attach_security_reports(&self, hits: &mut [MarketplaceSearchHit]) -> ModuleResult<()> {
+        let module_ids: Vec<String> = hits.iter().map(|hit| hit.listing.metadata.id.clone()).collect();
+        let reports = self.repository.list_module_security_reports(&module_ids).await?;
+        for hit in hits {
+            let metadata = &hit.listing.metadata;
+            hit.security = reports.iter()
+                .find(|report| report.module_id == metadata.id && report.version == metadata.version)
+                .map(ListingSecurity::from);
+        }
+        Ok(())
+    }
+
+    /// Compatible modules the tenant has not installed, ranked by how often
+    /// tenants install them alongside the tenant's modules, how well they fit
+    /// the categories the tenant uses and its profile, and their ratings
+    pub async fn recommend(&self, request: &RecommendationRequest) -> ModuleResult<Vec<ModuleRecommendation>> {
+        let installed: HashSet<String> = self.repository.list_tenant_instances(&request.tenant_id).await?
+            .into_iter()
+            .map(|instance| instance.module_id)
+            .collect();
+        let installed_ids: Vec<String> = installed.iter().cloned().collect();
+
+        let candidates = self.repository.search_marketplace_index(None, self.config.max_candidates).await?;
+        let co_installed: HashMap<String, u64> = self.repository
+            .list_co_installed_modules(&installed_ids, &request.tenant_id)
+            .await?
+            .into_iter()
+            .collect();
+        let max_co_installed = co_installed.values().copied().max().unwrap_or(0);
+
+        // Categories the tenant uses, weighted by how many of its modules
+        // are in them, plus the ones its profile is interested in
+        let mut category_weights: HashMap<String, f64> = HashMap::new();
+        for hit in candidates.iter().filter(|hit| installed.contains(&hit.listing.metadata.id)) {
+            for category in &hit.listing.metadata.categories {
+                *category_weights.entry(category_key(category)).or_insert(0.0) += 1.0;
+            }
+        }
+        for category in &request.profile.interests {
+            *category_weights.entry(category_key(category)).or_insert(0.0) += 2.0;
+        }
+        let max_category_weight = category_weights.values().copied().fold(0.0, f64::max);
+        let industry = request.profile.industry.as_deref().map(str::to_lowercase);
+
+        let mut recommendations: Vec<ModuleRecommendation> = candidates.into_iter()
+            .filter(|hit| !installed.contains(&hit.listing.metadata.id))
+            .filter(|hit| is_compatible(&hit.listing.metadata.adx_core_version, &self.platform_version))
+            .map(|hit| {
+                let listing = hit.listing;
+                let co_installs = co_installed.get(&listing.metadata.id).copied().unwrap_or(0);
+                let co_install_score = ratio(co_installs as f64, max_co_installed as f64);
+
+                let best_category = listing.metadata.categories.iter()
+                    .map(|category| (category, category_weights.get(&category_key(category)).copied().unwrap_or(0.0)))
+                    .max_by(|a, b| a.1.total_cmp(&b.1));
+                let category_score = best_category
+                    .map(|(_, weight)| ratio(weight, max_category_weight))
+                    .unwrap_or(0.0);
+
+                let industry_match = industry.as_ref().is_some_and(|industry| {
+                    listing.metadata.keywords.iter().any(|keyword| keyword.to_lowercase() == *industry)
+                });
+                let quality = bayesian_rating(&listing) / 5.0;
+
+                let score = 0.4 * co_install_score
+                    + 0.25 * category_score
+                    + if industry_match { 0.15 } else { 0.0 }
+                    + 0.2 * quality;
+
+                let reason = if co_installs > 0 && co_install_score >= category_score {
+                    format!("Installed alongside your modules by {} other tenants", co_installs)
+                } else if let Some((category, _)) = best_category.filter(|(_, weight)| *weight > 0.0) {
+                    format!("Popular in {}, which you use", category_key(category))
+                } else if industry_match {
+                    format!("Built for {}", request.profile.industry.as_deref().unwrap_or_default())
+                } else {
+                    format!("Rated {:.1} by {} reviewers", listing.rating_average, listing.review_count)
+                };
+
+                ModuleRecommendation {
+                    module_id: listing.metadata.id.clone(),
+                    score,
+                    reason,
+                    metadata: listing.metadata,
+                }
+            })
+            .collect();
+
+        recommendations.sort_by(|a, b| b.score.total_cmp(&a.score).then_with(|| a.module_id.cmp(&b.module_id)));
+        recommendations.truncate(request.limit.clamp(1, MAX_PAGE_SIZE) as usize);
+        Ok(recommendations)
+    }
+}
+
+/// The filters of a query, applied to the full-text hits
+struct Filters<'a> {
+    categories: HashSet<String>,
+    pricing_models: HashSet<String>,
+    min_rating: Option<f64>,
+    compatible_only: bool,
+    platform_version: &'a Version,
+    author: Option<String>,
+    keywords: HashSet<String>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Facet {
+    Category,
+    Pricing,
+    Rating,
+    Compatibility,
+}
+
+impl<'a> Filters<'a> {
+    fn new(query: &MarketplaceSearchQuery, platform_version: &'a Version) -> Self {
+        Self {
+            categories: query.categories.iter().map(category_key).collect(),
+            pricing_models: query.pricing_models.iter().map(pricing_key).collect(),
+            min_rating: query.min_rating,
+            compatible_only: query.compatible_only,
+            platform_version,
+            author: query.author.as_deref().map(str::to_lowercase),
+            keywords: query.keywords.iter().map(|keyword| keyword.to_lowercase()).collect(),
+        }
+    }
+
+    /// Whether the listing passes every filter, except those of `ignoring`
+    fn matches(&self, listing: &MarketplaceListing, ignoring: Option<Facet>) -> bool {
+        let metadata = &listing.metadata;
+        let applies = |facet| ignoring != Some(facet);
+
+        if applies(Facet::Category) && !self.categories.is_empty()
+            && !metadata.categories.iter().any(|category| self.categories.contains(&category_key(category))) {
+            return false;
+        }
+        if applies(Facet::Pricing) && !self.pricing_models.is_empty()
+            && !self.pricing_models.contains(&pricing_key(&listing.pricing_model)) {
+            return false;
+        }
+        if applies(Facet::Rating) && self.min_rating.is_some_and(|min_rating| listing.rating_average < min_rating) {
+            return false;
+        }
+        if applies(Facet::Compatibility) && self.compatible_only
+            && !is_compatible(&metadata.adx_core_version, self.platform_version) {
+            return false;
+        }
+        if let Some(author) = &self.author {
+            if !metadata.author.name.to_lowercase().contains(author) {
+                return false;
+            }
+        }
+        if !self.keywords.is_empty()
+            && !metadata.keywords.iter().any(|keyword| self.keywords.contains(&keyword.to_lowercase())) {
+            return false;
+        }
+        true
+    }
+
+    fn facets(&self, hits: &[IndexHit]) -> MarketplaceFacets {
+        let mut facets = MarketplaceFacets::default();
+        for hit in hits {
+            let listing = &hit.listing;
+            if self.matches(listing, Some(Facet::Category)) {
+                let categories: HashSet<String> = listing.metadata.categories.iter().map(category_key).collect();
+                for category in categories {
+                    *facets.categories.entry(category).or_insert(0) += 1;
+                }
+            }
+            if self.matches(listing, Some(Facet::Pricing)) {
+                *facets.pricing.entry(pricing_key(&listing.pricing_model)).or_insert(0) += 1;
+            }
+            if self.matches(listing, Some(Facet::Rating)) {
+                for stars in 1..=4 {
+                    if listing.rating_average >= stars as f64 {
+                        *facets.ratings.entry(format!("{}+", stars)).or_insert(0) += 1;
+                    }
+                }
+            }
+            if self.matches(listing, Some(Facet::Compatibility)) {
+                let key = if is_compatible(&listing.metadata.adx_core_version, self.platform_version) {
+                    "compatible"
+                } else {
+                    "incompatible"
+                };
+                *facets.compatibility.entry(key.to_string()).or_insert(0) += 1;
+            }
+        }
+        facets
+    }
+}
+
+/// Text relevance when there is a query, weighed with rating and installs so
+/// well-liked modules lead among similar matches
+fn relevance(hit: &IndexHit, max_rank: f64, max_installs: u64) -> f64 {
+    let quality = bayesian_rating(&hit.listing) / 5.0;
+    let popularity = ratio(
+        (1.0 + hit.listing.install_count as f64).ln(),
+        (1.0 + max_installs as f64).ln(),
+    );
+
+    if max_rank > 0.0 {
+        0.6 * hit.text_rank / max_rank + 0.25 * quality + 0.15 * popularity
+    } else {
+        0.6 * quality + 0.4 * popularity
+    }
+}
+
+fn sort_hits(hits: &mut [MarketplaceSearchHit], sort_by: &SortBy) {
+    match sort_by {
+        SortBy::Relevance => hits.sort_by(|a, b| b.score.total_cmp(&a.score)),
+        SortBy::Name => hits.sort_by(|a, b| a.listing.metadata.name.to_lowercase().cmp(&b.listing.metadata.name.to_lowercase())),
+        SortBy::Version => hits.sort_by(|a, b| b.listing.metadata.version.cmp(&a.listing.metadata.version)),
+        SortBy::Downloads => hits.sort_by(|a, b| b.listing.install_count.cmp(&a.listing.install_count)),
+        SortBy::Rating => hits.sort_by(|a, b| bayesian_rating(&b.listing).total_cmp(&bayesian_rating(&a.listing))),
+        SortBy::UpdatedAt => hits.sort_by(|a, b| b.listing.metadata.updated_at.cmp(&a.listing.metadata.updated_at)),
+        SortBy::CreatedAt => hits.sort_by(|a, b| b.listing.metadata.created_at.cmp(&a.listing.metadata.created_at)),
+    }
+}
+
+fn bayesian_rating(listing: &MarketplaceListing) -> f64 {
+    let reviews = listing.review_count as f64;
+    (PRIOR_RATING * PRIOR_REVIEWS + listing.rating_average * reviews) / (PRIOR_REVIEWS + reviews)
+}
+
+fn ratio(value: f64, max: f64) -> f64 {
+    if max > 0.0 { value / max } else { 0.0 }
+}
+
+pub fn is_compatible(requirement: &VersionRequirement, platform_version: &Version) -> bool {
+    if requirement.compatible_versions.contains(platform_version) {
+        return true;
+    }
+    *platform_version >= requirement.min_version
+        && requirement.max_version.as_ref().map_or(true, |max| platform_version <= max)
+}
+
+/// Key a category is indexed and faceted under
+pub fn category_key(category: &ModuleCategory) -> String {
+    match category {
+        ModuleCategory::Custom(name) => name.clone(),
+        other => format!("{:?}", other),
+    }
+}
+
+pub fn pricing_key(pricing_model: &PricingModel) -> String {
+    format!("{:?}", pricing_model)
+}
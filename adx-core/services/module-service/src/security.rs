@@ -1,20 +1,25 @@
 use async_trait::async_trait;
 use std::collections::HashMap;
+use std::time::Instant;
 use serde::{Deserialize, Serialize};
+use tokio::sync::RwLock;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
+use semver::Version;
+use tracing::warn;
 
 use crate::{
     ModuleResult, ModuleError, ModulePackage, ModuleSecurityScanner as ModuleSecurityScannerTrait,
     SecurityScanResult, SecurityPolicy, ScanType, ScanStatus, SecurityIssue, Severity, IssueCategory,
+    LockedDependency, ModulePermission,
 };
 
 /// Comprehensive security scanner for modules
 pub struct ModuleSecurityScanner {
     config: SecurityScannerConfig,
-    vulnerability_db: VulnerabilityDatabase,
     static_analyzer: StaticAnalyzer,
     dependency_scanner: DependencyScanner,
+    wasm_analyzer: WasmAnalyzer,
     malware_detector: MalwareDetector,
 }
 
@@ -26,7 +31,14 @@ pub struct SecurityScannerConfig {
     pub enable_configuration_analysis: bool,
     pub scan_timeout_seconds: u64,
     pub max_file_size_mb: u64,
+    /// OSV API that locked dependencies are looked up in
     pub vulnerability_db_url: String,
+    /// Packages scoring lower wait for a security review
+    pub min_security_score: u8,
+    /// Permissions packages may request without review; any when empty
+    pub allowed_permissions: Vec<String>,
+    /// Permissions packages are rejected for requesting
+    pub blocked_permissions: Vec<String>,
 }
 
 impl Default for SecurityScannerConfig {
@@ -38,7 +50,95 @@ impl Default for SecurityScannerConfig {
             enable_configuration_analysis: true,
             scan_timeout_seconds: 300,
             max_file_size_mb: 100,
-            vulnerability_db_url: "https://vulndb.adxcore.com".to_string(),
+            vulnerability_db_url: "https://api.osv.dev".to_string(),
+            min_security_score: 70,
+            allowed_permissions: vec![],
+            blocked_permissions: vec![],
+        }
+    }
+}
+
+/// Stages of a package scan, in the order they run
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ScanStage {
+    ManifestPolicy,
+    DependencyVulnerabilities,
+    WasmImports,
+    StaticAnalysis,
+    MalwareDetection,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub enum StageOutcome {
+    Passed,
+    IssuesFound,
+    Skipped,
+    /// The stage could not run; the package is held for review
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StageReport {
+    pub stage: ScanStage,
+    pub outcome: StageOutcome,
+    pub issue_count: u32,
+    pub error: Option<String>,
+    pub duration_ms: u64,
+}
+
+/// Where a scanned package stands with security review
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum ReviewStatus {
+    /// The scan passed; the package installs without review
+    NotRequired,
+    /// Waiting in the review queue; the package does not install
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl ReviewStatus {
+    /// Whether packages with this status may be installed
+    pub fn admits(&self) -> bool {
+        matches!(self, ReviewStatus::NotRequired | ReviewStatus::Approved)
+    }
+}
+
+/// The scan of one package, with each stage's outcome and its review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SecurityReport {
+    pub scan_id: Uuid,
+    pub module_id: String,
+    pub version: Version,
+    /// Checksum of the scanned package content
+    pub checksum: String,
+    pub score: u8,
+    pub issues: Vec<SecurityIssue>,
+    pub stages: Vec<StageReport>,
+    pub review_status: ReviewStatus,
+    /// Why the scan held or rejected the package
+    pub review_reasons: Vec<String>,
+    pub reviewer: Option<String>,
+    pub review_notes: Option<String>,
+    pub reviewed_at: Option<DateTime<Utc>>,
+    pub scanned_at: DateTime<Utc>,
+}
+
+impl SecurityReport {
+    pub fn count(&self, severity: Severity) -> u32 {
+        self.issues.iter().filter(|issue| issue.severity == severity).count() as u32
+    }
+
+    pub fn to_scan_result(&self) -> SecurityScanResult {
+        let failed = self.stages.iter().any(|stage| stage.outcome == StageOutcome::Failed);
+        SecurityScanResult {
+            scan_id: self.scan_id.to_string(),
+            module_id: self.module_id.clone(),
+            scan_type: ScanType::Static,
+            status: if failed { ScanStatus::Failed } else { ScanStatus::Completed },
+            issues: self.issues.clone(),
+            score: self.score,
+            scanned_at: self.scanned_at,
         }
     }
 }
@@ -46,63 +146,130 @@ impl Default for SecurityScannerConfig {
 impl ModuleSecurityScanner {
     pub fn new(config: SecurityScannerConfig) -> Self {
         Self {
-            vulnerability_db: VulnerabilityDatabase::new(&config.vulnerability_db_url),
             static_analyzer: StaticAnalyzer::new(),
-            dependency_scanner: DependencyScanner::new(),
+            dependency_scanner: DependencyScanner::new(&config.vulnerability_db_url, config.scan_timeout_seconds),
+            wasm_analyzer: WasmAnalyzer::new(),
             malware_detector: MalwareDetector::new(),
             config,
         }
     }
 
-    /// Perform comprehensive security scan
-    async fn perform_comprehensive_scan(&self, package: &ModulePackage) -> ModuleResult<SecurityScanResult> {
-        let scan_id = Uuid::new_v4().to_string();
+    /// Run every scan stage over a package. A stage that fails to run does
+    /// not stop the others; it holds the package for review instead.
+    pub async fn scan(&self, package: &ModulePackage) -> ModuleResult<SecurityReport> {
         let mut issues = Vec::new();
-        let mut total_score = 100u8;
-
-        // Static code analysis
-        if self.config.enable_static_analysis {
-            let static_issues = self.static_analyzer.analyze_package(package).await?;
-            let static_penalty = self.calculate_penalty(&static_issues);
-            total_score = total_score.saturating_sub(static_penalty);
-            issues.extend(static_issues);
+        let mut stages = Vec::new();
+
+        let size_mb = package.content.len() as u64 / (1024 * 1024);
+        if size_mb > self.config.max_file_size_mb {
+            return Err(ModuleError::SecurityScanFailed(format!(
+                "{} {} is larger than the {} MB the scanner accepts",
+                package.metadata.id, package.metadata.version, self.config.max_file_size_mb
+            )));
         }
 
-        // Dependency vulnerability scanning
-        if self.config.enable_dependency_scanning {
-            let dependency_issues = self.dependency_scanner.scan_dependencies(package).await?;
-            let dependency_penalty = self.calculate_penalty(&dependency_issues);
-            total_score = total_score.saturating_sub(dependency_penalty);
-            issues.extend(dependency_issues);
-        }
+        for stage in [
+            ScanStage::ManifestPolicy,
+            ScanStage::DependencyVulnerabilities,
+            ScanStage::WasmImports,
+            ScanStage::StaticAnalysis,
+            ScanStage::MalwareDetection,
+        ] {
+            let started = Instant::now();
+            let result = match stage {
+                ScanStage::ManifestPolicy if self.config.enable_configuration_analysis => {
+                    Some(self.analyze_configuration(package).await)
+                }
+                ScanStage::DependencyVulnerabilities if self.config.enable_dependency_scanning => {
+                    Some(self.dependency_scanner.scan_dependencies(package).await)
+                }
+                ScanStage::WasmImports if self.config.enable_static_analysis && is_wasm(package) => {
+                    Some(self.wasm_analyzer.analyze_imports(package).await)
+                }
+                ScanStage::StaticAnalysis if self.config.enable_static_analysis => {
+                    Some(self.static_analyzer.analyze_package(package).await)
+                }
+                ScanStage::MalwareDetection if self.config.enable_malware_detection => {
+                    Some(self.malware_detector.scan_for_malware(package).await)
+                }
+                _ => None,
+            };
 
-        // Malware detection
-        if self.config.enable_malware_detection {
-            let malware_issues = self.malware_detector.scan_for_malware(package).await?;
-            let malware_penalty = self.calculate_penalty(&malware_issues);
-            total_score = total_score.saturating_sub(malware_penalty);
-            issues.extend(malware_issues);
+            let (outcome, issue_count, error) = match result {
+                None => (StageOutcome::Skipped, 0, None),
+                Some(Ok(stage_issues)) if stage_issues.is_empty() => (StageOutcome::Passed, 0, None),
+                Some(Ok(stage_issues)) => {
+                    let count = stage_issues.len() as u32;
+                    issues.extend(stage_issues);
+                    (StageOutcome::IssuesFound, count, None)
+                }
+                Some(Err(e)) => {
+                    warn!("Security scan stage {:?} failed for {}: {}", stage, package.metadata.id, e);
+                    (StageOutcome::Failed, 0, Some(e.to_string()))
+                }
+            };
+            stages.push(StageReport {
+                stage,
+                outcome,
+                issue_count,
+                error,
+                duration_ms: started.elapsed().as_millis() as u64,
+            });
         }
 
-        // Configuration analysis
-        if self.config.enable_configuration_analysis {
-            let config_issues = self.analyze_configuration(package).await?;
-            let config_penalty = self.calculate_penalty(&config_issues);
-            total_score = total_score.saturating_sub(config_penalty);
-            issues.extend(config_issues);
-        }
+        let score = 100u8.saturating_sub(self.calculate_penalty(&issues));
+        let (review_status, review_reasons) = self.review_verdict(&issues, &stages, score);
 
-        Ok(SecurityScanResult {
-            scan_id,
+        Ok(SecurityReport {
+            scan_id: Uuid::new_v4(),
             module_id: package.metadata.id.clone(),
-            scan_type: ScanType::Static,
-            status: ScanStatus::Completed,
+            version: package.metadata.version.clone(),
+            checksum: package.checksum.clone(),
+            score,
             issues,
-            score: total_score,
+            stages,
+            review_status,
+            review_reasons,
+            reviewer: None,
+            review_notes: None,
+            reviewed_at: None,
             scanned_at: Utc::now(),
         })
     }
 
+    /// Critical issues reject a package outright. High issues, a low score
+    /// or a stage that could not run send it to a reviewer.
+    fn review_verdict(&self, issues: &[SecurityIssue], stages: &[StageReport], score: u8) -> (ReviewStatus, Vec<String>) {
+        let critical: Vec<String> = issues.iter()
+            .filter(|issue| issue.severity == Severity::Critical)
+            .map(|issue| format!("Critical: {}", issue.title))
+            .collect();
+        if !critical.is_empty() {
+            return (ReviewStatus::Rejected, critical);
+        }
+
+        let mut reasons: Vec<String> = issues.iter()
+            .filter(|issue| issue.severity == Severity::High)
+            .map(|issue| format!("High: {}", issue.title))
+            .collect();
+        for stage in stages.iter().filter(|stage| stage.outcome == StageOutcome::Failed) {
+            reasons.push(format!(
+                "{:?} could not run: {}",
+                stage.stage,
+                stage.error.as_deref().unwrap_or("unknown error")
+            ));
+        }
+        if score < self.config.min_security_score {
+            reasons.push(format!("Score {} is below {}", score, self.config.min_security_score));
+        }
+
+        if reasons.is_empty() {
+            (ReviewStatus::NotRequired, reasons)
+        } else {
+            (ReviewStatus::Pending, reasons)
+        }
+    }
+
     fn calculate_penalty(&self, issues: &[SecurityIssue]) -> u8 {
         let mut penalty = 0u8;
         
@@ -139,6 +306,35 @@ impl ModuleSecurityScanner {
             }
         }
 
+        // Check permissions against the platform's policy
+        for permission in &package.manifest.permissions {
+            let name = permission_name(permission);
+            if self.config.blocked_permissions.iter().any(|blocked| blocked == name) {
+                issues.push(SecurityIssue {
+                    id: Uuid::new_v4().to_string(),
+                    severity: Severity::Critical,
+                    category: IssueCategory::PrivilegeEscalation,
+                    title: format!("Blocked permission {} requested", name),
+                    description: format!("Module requests {:?}, which this platform blocks", permission),
+                    recommendation: "Remove the permission from the manifest".to_string(),
+                    cve_id: None,
+                    affected_files: vec!["manifest.json".to_string()],
+                });
+            } else if !self.config.allowed_permissions.is_empty()
+                && !self.config.allowed_permissions.iter().any(|allowed| allowed == name) {
+                issues.push(SecurityIssue {
+                    id: Uuid::new_v4().to_string(),
+                    severity: Severity::High,
+                    category: IssueCategory::ConfigurationIssue,
+                    title: format!("Permission {} outside the allowed set", name),
+                    description: format!("Module requests {:?}, which needs a reviewer's approval", permission),
+                    recommendation: "Justify the permission in the module's documentation".to_string(),
+                    cve_id: None,
+                    affected_files: vec!["manifest.json".to_string()],
+                });
+            }
+        }
+
         // Check resource limits
         if package.manifest.resources.max_memory_mb > 2048 {
             issues.push(SecurityIssue {
@@ -172,12 +368,12 @@ impl ModuleSecurityScanner {
         Ok(issues)
     }
 
-    fn is_dangerous_permission(&self, permission: &crate::ModulePermission) -> bool {
+    fn is_dangerous_permission(&self, permission: &ModulePermission) -> bool {
         match permission {
-            crate::ModulePermission::SystemAccess(_) => true,
-            crate::ModulePermission::AdminAccess => true,
-            crate::ModulePermission::ModuleManagement => true,
-            crate::ModulePermission::NetworkAccess(domain) if domain == "*" => true,
+            ModulePermission::SystemAccess(_) => true,
+            ModulePermission::AdminAccess => true,
+            ModulePermission::ModuleManagement => true,
+            ModulePermission::NetworkAccess(domain) if domain == "*" => true,
             _ => false,
         }
     }
@@ -186,7 +382,7 @@ impl ModuleSecurityScanner {
 #[async_trait]
 impl ModuleSecurityScannerTrait for ModuleSecurityScanner {
     async fn scan_package(&self, package: &ModulePackage) -> ModuleResult<SecurityScanResult> {
-        self.perform_comprehensive_scan(package).await
+        Ok(self.scan(package).await?.to_scan_result())
     }
 
     async fn scan_runtime(&self, instance_id: Uuid) -> ModuleResult<SecurityScanResult> {
@@ -241,29 +437,169 @@ impl ModuleSecurityScannerTrait for ModuleSecurityScanner {
 
 // Supporting components
 
+/// Client of an OSV vulnerability database, https://osv.dev
 pub struct VulnerabilityDatabase {
     base_url: String,
-    cache: HashMap<String, VulnerabilityInfo>,
+    client: reqwest::Client,
+    cache: RwLock<HashMap<String, VulnerabilityInfo>>,
+}
+
+/// Most queries OSV takes in one batch
+const OSV_BATCH_SIZE: usize = 1000;
+
+#[derive(Debug, Deserialize)]
+struct OsvBatchResponse {
+    results: Vec<OsvBatchResult>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct OsvBatchResult {
+    #[serde(default)]
+    vulns: Vec<OsvVulnerabilityRef>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvVulnerabilityRef {
+    id: String,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvVulnerability {
+    id: String,
+    #[serde(default)]
+    summary: Option<String>,
+    #[serde(default)]
+    details: Option<String>,
+    #[serde(default)]
+    aliases: Vec<String>,
+    #[serde(default)]
+    affected: Vec<OsvAffected>,
+    #[serde(default)]
+    database_specific: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvAffected {
+    #[serde(default)]
+    ranges: Vec<OsvRange>,
+    #[serde(default)]
+    versions: Vec<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct OsvRange {
+    #[serde(default)]
+    events: Vec<HashMap<String, String>>,
 }
 
 impl VulnerabilityDatabase {
-    pub fn new(base_url: &str) -> Self {
+    pub fn new(base_url: &str, timeout_seconds: u64) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(timeout_seconds))
+            .build()
+            .unwrap_or_default();
+
         Self {
-            base_url: base_url.to_string(),
-            cache: HashMap::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client,
+            cache: RwLock::new(HashMap::new()),
         }
     }
 
-    pub async fn check_vulnerability(&self, component: &str, version: &str) -> ModuleResult<Option<VulnerabilityInfo>> {
-        let cache_key = format!("{}:{}", component, version);
-        
-        if let Some(cached) = self.cache.get(&cache_key) {
-            return Ok(Some(cached.clone()));
+    /// Known vulnerabilities of each dependency, in the order given
+    pub async fn check_vulnerabilities(&self, dependencies: &[LockedDependency]) -> ModuleResult<Vec<Vec<VulnerabilityInfo>>> {
+        let mut found = Vec::with_capacity(dependencies.len());
+
+        for batch in dependencies.chunks(OSV_BATCH_SIZE) {
+            let queries: Vec<serde_json::Value> = batch.iter()
+                .map(|dependency| serde_json::json!({
+                    "package": { "name": dependency.name, "ecosystem": dependency.ecosystem },
+                    "version": dependency.version,
+                }))
+                .collect();
+
+            let response = self.client
+                .post(format!("{}/v1/querybatch", self.base_url))
+                .json(&serde_json::json!({ "queries": queries }))
+                .send()
+                .await?;
+            if !response.status().is_success() {
+                return Err(ModuleError::NetworkError(
+                    format!("Vulnerability lookup failed: {}", response.status())
+                ));
+            }
+            let response: OsvBatchResponse = response.json().await?;
+            if response.results.len() != batch.len() {
+                return Err(ModuleError::NetworkError(format!(
+                    "Vulnerability lookup answered {} of {} queries", response.results.len(), batch.len()
+                )));
+            }
+
+            for result in response.results {
+                let mut vulnerabilities = Vec::with_capacity(result.vulns.len());
+                for vulnerability in result.vulns {
+                    vulnerabilities.push(self.get_vulnerability(&vulnerability.id).await?);
+                }
+                found.push(vulnerabilities);
+            }
+        }
+
+        Ok(found)
+    }
+
+    /// Details of a vulnerability; batch queries only return ids
+    async fn get_vulnerability(&self, id: &str) -> ModuleResult<VulnerabilityInfo> {
+        if let Some(cached) = self.cache.read().await.get(id) {
+            return Ok(cached.clone());
+        }
+
+        let response = self.client
+            .get(format!("{}/v1/vulns/{}", self.base_url, id))
+            .send()
+            .await?;
+        if !response.status().is_success() {
+            return Err(ModuleError::NetworkError(
+                format!("Failed to get vulnerability {}: {}", id, response.status())
+            ));
         }
+        let vulnerability: OsvVulnerability = response.json().await?;
+
+        let info = VulnerabilityInfo {
+            cve_id: vulnerability.aliases.iter()
+                .find(|alias| alias.starts_with("CVE-"))
+                .cloned()
+                .unwrap_or_else(|| vulnerability.id.clone()),
+            severity: osv_severity(vulnerability.database_specific.as_ref()),
+            description: vulnerability.summary
+                .or(vulnerability.details)
+                .unwrap_or_else(|| vulnerability.id.clone()),
+            affected_versions: vulnerability.affected.iter()
+                .flat_map(|affected| affected.versions.iter().cloned())
+                .collect(),
+            fixed_versions: vulnerability.affected.iter()
+                .flat_map(|affected| affected.ranges.iter())
+                .flat_map(|range| range.events.iter())
+                .filter_map(|event| event.get("fixed").cloned())
+                .collect(),
+        };
+
+        self.cache.write().await.insert(id.to_string(), info.clone());
+        Ok(info)
+    }
+}
 
-        // Query vulnerability database
-        // This would make HTTP requests to the vulnerability database
-        Ok(None)
+/// Severity from the advisory database's rating; unrated vulnerabilities
+/// count as medium
+fn osv_severity(database_specific: Option<&serde_json::Value>) -> Severity {
+    let rating = database_specific
+        .and_then(|specific| specific.get("severity"))
+        .and_then(|severity| severity.as_str())
+        .map(str::to_ascii_uppercase);
+    match rating.as_deref() {
+        Some("CRITICAL") => Severity::Critical,
+        Some("HIGH") => Severity::High,
+        Some("LOW") => Severity::Low,
+        _ => Severity::Medium,
     }
 }
 
@@ -332,10 +668,10 @@ impl StaticAnalyzer {
         
         // Simple pattern matching for common secret patterns
         let secret_patterns = [
-            r"api[_-]?key\s*[:=]\s*['\"][a-zA-Z0-9]{20,}['\"]",
-            r"password\s*[:=]\s*['\"][^'\"]{8,}['\"]",
-            r"secret\s*[:=]\s*['\"][a-zA-Z0-9]{16,}['\"]",
-            r"token\s*[:=]\s*['\"][a-zA-Z0-9]{20,}['\"]",
+            r#"api[_-]?key\s*[:=]\s*['"][a-zA-Z0-9]{20,}['"]"#,
+            r#"password\s*[:=]\s*['"][^'"]{8,}['"]"#,
+            r#"secret\s*[:=]\s*['"][a-zA-Z0-9]{16,}['"]"#,
+            r#"token\s*[:=]\s*['"][a-zA-Z0-9]{20,}['"]"#,
         ];
 
         for pattern in &secret_patterns {
@@ -374,30 +710,38 @@ pub struct DependencyScanner {
 }
 
 impl DependencyScanner {
-    pub fn new() -> Self {
+    pub fn new(vulnerability_db_url: &str, timeout_seconds: u64) -> Self {
         Self {
-            vulnerability_db: VulnerabilityDatabase::new("https://vulndb.adxcore.com"),
+            vulnerability_db: VulnerabilityDatabase::new(vulnerability_db_url, timeout_seconds),
         }
     }
 
+    /// Look the libraries the package was built with up for known
+    /// vulnerabilities
     pub async fn scan_dependencies(&self, package: &ModulePackage) -> ModuleResult<Vec<SecurityIssue>> {
         let mut issues = Vec::new();
+        if package.locked_dependencies.is_empty() {
+            return Ok(issues);
+        }
 
-        // Scan each dependency for known vulnerabilities
-        for dependency in &package.manifest.dependencies {
-            if let Some(vuln_info) = self.vulnerability_db
-                .check_vulnerability(&dependency.module_id, &dependency.version_requirement)
-                .await? {
-                
+        let vulnerabilities = self.vulnerability_db
+            .check_vulnerabilities(&package.locked_dependencies)
+            .await?;
+        for (dependency, vulnerabilities) in package.locked_dependencies.iter().zip(vulnerabilities) {
+            for vuln_info in vulnerabilities {
                 issues.push(SecurityIssue {
                     id: Uuid::new_v4().to_string(),
                     severity: vuln_info.severity,
                     category: IssueCategory::DependencyIssue,
-                    title: format!("Vulnerable dependency: {}", dependency.module_id),
+                    title: format!("Vulnerable dependency: {} {} ({})", dependency.name, dependency.version, vuln_info.cve_id),
                     description: vuln_info.description,
-                    recommendation: format!("Update to version: {:?}", vuln_info.fixed_versions),
+                    recommendation: if vuln_info.fixed_versions.is_empty() {
+                        format!("Replace {}; no fixed version is known", dependency.name)
+                    } else {
+                        format!("Update {} to one of: {}", dependency.name, vuln_info.fixed_versions.join(", "))
+                    },
                     cve_id: Some(vuln_info.cve_id),
-                    affected_files: vec!["dependencies".to_string()],
+                    affected_files: vec![format!("{}:{}", dependency.ecosystem, dependency.name)],
                 });
             }
         }
@@ -406,6 +750,130 @@ impl DependencyScanner {
     }
 }
 
+/// Host modules the WASM sandbox links imports from
+const WASI_MODULES: [&str; 2] = ["wasi_snapshot_preview1", "wasi_unstable"];
+
+/// Checks what a WebAssembly backend imports from its host: only WASI is
+/// linked, and its filesystem, socket and process calls need matching
+/// permissions in the manifest
+pub struct WasmAnalyzer {
+    engine: wasmtime::Engine,
+}
+
+impl WasmAnalyzer {
+    pub fn new() -> Self {
+        Self {
+            engine: wasmtime::Engine::default(),
+        }
+    }
+
+    pub async fn analyze_imports(&self, package: &ModulePackage) -> ModuleResult<Vec<SecurityIssue>> {
+        let engine = self.engine.clone();
+        let content = package.content.clone();
+        let imports = tokio::task::spawn_blocking(move || {
+            wasmtime::Module::from_binary(&engine, &content).map(|module| {
+                module.imports()
+                    .map(|import| (import.module().to_string(), import.name().to_string()))
+                    .collect::<Vec<_>>()
+            })
+        })
+        .await
+        .map_err(|e| ModuleError::InternalError(e.to_string()))?;
+
+        let imports = match imports {
+            Ok(imports) => imports,
+            Err(e) => {
+                return Ok(vec![wasm_issue(
+                    Severity::Critical,
+                    IssueCategory::MaliciousCode,
+                    "Invalid WebAssembly".to_string(),
+                    format!("The backend does not validate as WebAssembly: {}", e),
+                    "Rebuild the module for wasm32-wasi".to_string(),
+                )]);
+            }
+        };
+
+        let permissions = &package.manifest.permissions;
+        let has_network = permissions.iter().any(|p| matches!(p, ModulePermission::NetworkAccess(_)));
+        let has_file_read = permissions.iter().any(|p| matches!(p, ModulePermission::FileRead(_) | ModulePermission::FileWrite(_)));
+        let has_file_write = permissions.iter().any(|p| matches!(p, ModulePermission::FileWrite(_)));
+
+        let mut issues = Vec::new();
+        for (module, name) in imports {
+            if !WASI_MODULES.contains(&module.as_str()) {
+                issues.push(wasm_issue(
+                    Severity::High,
+                    IssueCategory::PrivilegeEscalation,
+                    format!("Import from unknown host module {}", module),
+                    format!("The backend imports {}::{}, which the sandbox does not provide", module, name),
+                    "Only import WASI functions".to_string(),
+                ));
+                continue;
+            }
+
+            let issue = match name.as_str() {
+                "sock_accept" | "sock_recv" | "sock_send" | "sock_shutdown" if !has_network => Some((
+                    Severity::High,
+                    IssueCategory::DataExfiltration,
+                    "Undeclared network access",
+                )),
+                "path_open" | "path_filestat_get" | "path_readlink" | "fd_readdir" if !has_file_read => Some((
+                    Severity::Medium,
+                    IssueCategory::ConfigurationIssue,
+                    "Undeclared filesystem access",
+                )),
+                "path_create_directory" | "path_remove_directory" | "path_rename" | "path_symlink"
+                | "path_link" | "path_unlink_file" | "path_filestat_set_times" if !has_file_write => Some((
+                    Severity::High,
+                    IssueCategory::ConfigurationIssue,
+                    "Undeclared filesystem writes",
+                )),
+                "proc_raise" => Some((
+                    Severity::Medium,
+                    IssueCategory::ResourceAbuse,
+                    "Raises process signals",
+                )),
+                "environ_get" | "environ_sizes_get" => Some((
+                    Severity::Low,
+                    IssueCategory::DataExfiltration,
+                    "Reads the environment",
+                )),
+                _ => None,
+            };
+            if let Some((severity, category, title)) = issue {
+                issues.push(wasm_issue(
+                    severity,
+                    category,
+                    title.to_string(),
+                    format!("The backend imports {}::{}", module, name),
+                    "Declare the permission in the manifest or remove the call".to_string(),
+                ));
+            }
+        }
+
+        Ok(issues)
+    }
+}
+
+fn wasm_issue(
+    severity: Severity,
+    category: IssueCategory,
+    title: String,
+    description: String,
+    recommendation: String,
+) -> SecurityIssue {
+    SecurityIssue {
+        id: Uuid::new_v4().to_string(),
+        severity,
+        category,
+        title,
+        description,
+        recommendation,
+        cve_id: None,
+        affected_files: vec!["backend entry".to_string()],
+    }
+}
+
 pub struct MalwareDetector {
     // Malware detection engine
 }
@@ -472,9 +940,9 @@ impl MalwareDetector {
         // Check for suspicious patterns
         let suspicious_patterns = [
             r"crypto\s*\.\s*createHash",
-            r"require\s*\(\s*['\"]child_process['\"]",
-            r"fs\s*\.\s*readFileSync\s*\(\s*['\"][^'\"]*passwd[^'\"]*['\"]",
-            r"process\s*\.\s*env\s*\[\s*['\"]HOME['\"]",
+            r#"require\s*\(\s*['"]child_process['"]"#,
+            r#"fs\s*\.\s*readFileSync\s*\(\s*['"][^'"]*passwd[^'"]*['"]"#,
+            r#"process\s*\.\s*env\s*\[\s*['"]HOME['"]"#,
         ];
 
         for pattern in &suspicious_patterns {
@@ -485,4 +953,28 @@ impl MalwareDetector {
 
         false
     }
-}
\ No newline at end of file
+}
+
+/// Whether a package's backend is WebAssembly
+fn is_wasm(package: &ModulePackage) -> bool {
+    package.content.starts_with(b"\0asm")
+        || package.manifest.extension_points.backend_entry.as_deref().is_some_and(|entry| entry.ends_with(".wasm"))
+}
+
+/// Name permissions are allowed and blocked by in the security config
+fn permission_name(permission: &ModulePermission) -> &'static str {
+    match permission {
+        ModulePermission::DatabaseRead(_) => "DatabaseRead",
+        ModulePermission::DatabaseWrite(_) => "DatabaseWrite",
+        ModulePermission::FileRead(_) => "FileRead",
+        ModulePermission::FileWrite(_) => "FileWrite",
+        ModulePermission::NetworkAccess(_) => "NetworkAccess",
+        ModulePermission::SystemAccess(_) => "SystemAccess",
+        ModulePermission::UserDataAccess => "UserDataAccess",
+        ModulePermission::TenantDataAccess => "TenantDataAccess",
+        ModulePermission::WorkflowExecution(_) => "WorkflowExecution",
+        ModulePermission::ApiAccess(_) => "ApiAccess",
+        ModulePermission::ModuleManagement => "ModuleManagement",
+        ModulePermission::AdminAccess => "AdminAccess",
+    }
+}
@@ -0,0 +1,121 @@
+use chrono::{DateTime, Utc};
+use jsonschema::JSONSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use uuid::Uuid;
+
+use crate::{ModuleResult, ModuleError, ModuleConfiguration};
+
+/// A version of a module instance's settings. Every change adds a version;
+/// the latest is the instance's configuration.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleSettings {
+    pub instance_id: Uuid,
+    pub version: u32,
+    pub values: Value,
+    pub updated_by: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// What a settings page renders: the current settings and the schema and
+/// keys the module declares
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleSettingsView {
+    pub current: ModuleSettings,
+    pub schema: Value,
+    pub defaults: Value,
+    pub required: Vec<String>,
+    pub tenant_configurable: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateSettingsRequest {
+    /// Keys to set; others keep their current value
+    pub values: Map<String, Value>,
+    pub updated_by: String,
+    /// Refuse the change if the settings are no longer at this version
+    #[serde(default)]
+    pub expected_version: Option<u32>,
+}
+
+/// Check that a tenant only sets keys the module lets tenants configure
+pub fn check_tenant_keys(configuration: &ModuleConfiguration, changes: &Value) -> ModuleResult<()> {
+    let changes = match changes {
+        Value::Object(changes) => changes,
+        Value::Null => return Ok(()),
+        _ => return Err(ModuleError::ValidationFailed("Module settings must be an object".to_string())),
+    };
+
+    let denied: Vec<&str> = changes.keys()
+        .filter(|key| {
+            !configuration.tenant_configurable.contains(key) && !configuration.required_config.contains(key)
+        })
+        .map(String::as_str)
+        .collect();
+    if !denied.is_empty() {
+        return Err(ModuleError::ValidationFailed(
+            format!("Settings not configurable by tenants: {}", denied.join(", "))
+        ));
+    }
+    Ok(())
+}
+
+/// `base` with the top-level keys of `changes` set
+pub fn merge_settings(base: &Value, changes: &Value) -> Value {
+    let mut merged = match base {
+        Value::Object(base) => base.clone(),
+        _ => Map::new(),
+    };
+    if let Value::Object(changes) = changes {
+        for (key, value) in changes {
+            merged.insert(key.clone(), value.clone());
+        }
+    }
+    Value::Object(merged)
+}
+
+/// Check settings against the required keys and JSON schema the module
+/// declares
+pub fn validate_settings(configuration: &ModuleConfiguration, settings: &Value) -> ModuleResult<()> {
+    let missing: Vec<&str> = configuration.required_config.iter()
+        .filter(|key| settings.get(key.as_str()).map_or(true, Value::is_null))
+        .map(String::as_str)
+        .collect();
+    if !missing.is_empty() {
+        return Err(ModuleError::ValidationFailed(
+            format!("Missing required settings: {}", missing.join(", "))
+        ));
+    }
+
+    if configuration.config_schema.is_null() {
+        return Ok(());
+    }
+    let schema = JSONSchema::compile(&configuration.config_schema)
+        .map_err(|e| ModuleError::ValidationFailed(format!("Module settings schema is invalid: {}", e)))?;
+    if let Err(errors) = schema.validate(settings) {
+        let errors: Vec<String> = errors.map(|error| error.to_string()).collect();
+        return Err(ModuleError::ValidationFailed(
+            format!("Settings do not match the module's schema: {}", errors.join("; "))
+        ));
+    }
+    Ok(())
+}
+
+/// Keys whose value differs between two settings, with the old and new value
+pub fn changed_settings(old: &Value, new: &Value) -> Vec<(String, Value, Value)> {
+    let empty = Map::new();
+    let old = old.as_object().unwrap_or(&empty);
+    let new = new.as_object().unwrap_or(&empty);
+
+    let mut keys: Vec<&String> = old.keys().chain(new.keys()).collect();
+    keys.sort();
+    keys.dedup();
+
+    keys.into_iter()
+        .filter_map(|key| {
+            let old_value = old.get(key).cloned().unwrap_or(Value::Null);
+            let new_value = new.get(key).cloned().unwrap_or(Value::Null);
+            (old_value != new_value).then(|| (key.clone(), old_value, new_value))
+        })
+        .collect()
+}
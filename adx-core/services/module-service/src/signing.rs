@@ -0,0 +1,331 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
+use chrono::{DateTime, Utc};
+use ring::rand::SystemRandom;
+use ring::signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use tracing::warn;
+
+use crate::{ModuleResult, ModuleError, ModulePackage, PackageAsset, LockedDependency, ModuleRepository};
+
+/// Signatures over a module package. The developer signs the package's id,
+/// version and checksum, and the digest of its frontend assets if it has
+/// any; the marketplace countersigns that together with the developer's
+/// signature once it has reviewed the binary.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PackageSignature {
+    /// Id of the developer key the package was signed with
+    pub key_id: String,
+    /// Base64 Ed25519 signature
+    pub signature: String,
+    pub countersignature: Option<Countersignature>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Countersignature {
+    /// Id of the marketplace key the package was countersigned with
+    pub key_id: String,
+    /// Base64 Ed25519 signature
+    pub signature: String,
+}
+
+/// A developer's public signing key, registered before they publish
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeveloperKey {
+    pub key_id: String,
+    pub developer_id: String,
+    /// Base64 raw Ed25519 public key
+    pub public_key: String,
+    pub created_at: DateTime<Utc>,
+}
+
+impl DeveloperKey {
+    pub fn validate(&self) -> ModuleResult<()> {
+        if self.key_id.trim().is_empty() || self.developer_id.trim().is_empty() {
+            return Err(ModuleError::ValidationFailed(
+                "Developer key requires a key id and a developer id".to_string()
+            ));
+        }
+
+        match BASE64.decode(&self.public_key) {
+            Ok(key) if key.len() == ED25519_PUBLIC_KEY_LEN => Ok(()),
+            _ => Err(ModuleError::ValidationFailed(
+                format!("Public key of {} is not a base64 Ed25519 public key", self.key_id)
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum RevocationKind {
+    /// `value` is a developer key id
+    DeveloperKey,
+    /// `value` is a marketplace key id
+    MarketplaceKey,
+    /// `value` is a package checksum
+    Package,
+}
+
+impl RevocationKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RevocationKind::DeveloperKey => "developer_key",
+            RevocationKind::MarketplaceKey => "marketplace_key",
+            RevocationKind::Package => "package",
+        }
+    }
+
+    pub fn parse(kind: &str) -> ModuleResult<Self> {
+        match kind {
+            "developer_key" => Ok(RevocationKind::DeveloperKey),
+            "marketplace_key" => Ok(RevocationKind::MarketplaceKey),
+            "package" => Ok(RevocationKind::Package),
+            other => Err(ModuleError::SerializationError(format!("Unknown revocation kind {}", other))),
+        }
+    }
+}
+
+/// An entry in the revocation list. Packages signed with a revoked key, and
+/// revoked packages, are no longer installed.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Revocation {
+    pub kind: RevocationKind,
+    pub value: String,
+    pub reason: Option<String>,
+    pub revoked_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RegisterDeveloperKeyRequest {
+    pub key_id: String,
+    pub developer_id: String,
+    pub public_key: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevokeRequest {
+    pub kind: RevocationKind,
+    pub value: String,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone)]
+pub struct PackageVerifierConfig {
+    /// Refuse packages without a signature. Signed packages are always
+    /// verified.
+    pub require_signatures: bool,
+    /// Marketplace countersigning keys by key id, as base64 raw Ed25519
+    /// public keys
+    pub marketplace_keys: HashMap<String, String>,
+}
+
+const ED25519_PUBLIC_KEY_LEN: usize = 32;
+
+/// Hex SHA-256 of package content, as carried in `ModulePackage::checksum`
+pub fn package_checksum(content: &[u8]) -> String {
+    format!("{:x}", Sha256::digest(content))
+}
+
+/// Signs packages with an Ed25519 key: developers sign what they publish,
+/// the marketplace countersigns what it has reviewed
+pub struct PackageSigner {
+    key_id: String,
+    key_pair: Ed25519KeyPair,
+}
+
+impl PackageSigner {
+    pub fn from_pkcs8(key_id: &str, pkcs8: &[u8]) -> ModuleResult<Self> {
+        let key_pair = Ed25519KeyPair::from_pkcs8(pkcs8)
+            .map_err(|e| ModuleError::ConfigurationError(format!("Invalid signing key {}: {}", key_id, e)))?;
+
+        Ok(Self {
+            key_id: key_id.to_string(),
+            key_pair,
+        })
+    }
+
+    /// A new PKCS#8 encoded Ed25519 key
+    pub fn generate_pkcs8() -> ModuleResult<Vec<u8>> {
+        let document = Ed25519KeyPair::generate_pkcs8(&SystemRandom::new())
+            .map_err(|e| ModuleError::InternalError(format!("Failed to generate signing key: {}", e)))?;
+        Ok(document.as_ref().to_vec())
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Base64 public key, as registered for the key id
+    pub fn public_key(&self) -> String {
+        BASE64.encode(self.key_pair.public_key().as_ref())
+    }
+
+    /// Sign a package as its developer. Sets the checksum from the content
+    /// and drops any earlier signature.
+    pub fn sign(&self, package: &mut ModulePackage) {
+        package.checksum = package_checksum(&package.content);
+        let signature = self.key_pair.sign(&developer_payload(package));
+
+        package.signature = Some(PackageSignature {
+            key_id: self.key_id.clone(),
+            signature: BASE64.encode(signature.as_ref()),
+            countersignature: None,
+        });
+    }
+
+    /// Countersign a developer-signed package as the marketplace
+    pub fn countersign(&self, package: &mut ModulePackage) -> ModuleResult<()> {
+        if package_checksum(&package.content) != package.checksum {
+            return Err(ModuleError::ValidationFailed(
+                format!("Checksum of {} {} does not match its contents", package.metadata.id, package.metadata.version)
+            ));
+        }
+
+        let payload = match &package.signature {
+            Some(signature) => marketplace_payload(package, signature),
+            None => return Err(ModuleError::ValidationFailed(
+                format!("{} {} has not been signed by its developer", package.metadata.id, package.metadata.version)
+            )),
+        };
+        let countersignature = self.key_pair.sign(&payload);
+
+        if let Some(signature) = package.signature.as_mut() {
+            signature.countersignature = Some(Countersignature {
+                key_id: self.key_id.clone(),
+                signature: BASE64.encode(countersignature.as_ref()),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Checks at installation that a package's content matches its checksum, and
+/// that it carries a valid developer signature and marketplace countersignature
+/// from keys that have not been revoked
+pub struct PackageVerifier {
+    config: PackageVerifierConfig,
+    repository: Arc<dyn ModuleRepository>,
+}
+
+impl PackageVerifier {
+    pub fn new(config: PackageVerifierConfig, repository: Arc<dyn ModuleRepository>) -> Self {
+        Self { config, repository }
+    }
+
+    pub async fn verify(&self, package: &ModulePackage) -> ModuleResult<()> {
+        let name = format!("{} {}", package.metadata.id, package.metadata.version);
+
+        let checksum = package_checksum(&package.content);
+        if !checksum.eq_ignore_ascii_case(&package.checksum) {
+            return Err(ModuleError::SignatureVerificationFailed(
+                format!("Checksum of {} does not match its contents", name)
+            ));
+        }
+        if self.repository.is_revoked(RevocationKind::Package, &checksum).await? {
+            return Err(ModuleError::SignatureVerificationFailed(format!("{} has been revoked", name)));
+        }
+
+        let signature = match &package.signature {
+            Some(signature) => signature,
+            None if self.config.require_signatures => {
+                return Err(ModuleError::SignatureVerificationFailed(format!("{} is not signed", name)));
+            }
+            None => {
+                warn!("Package {} is not signed", name);
+                return Ok(());
+            }
+        };
+
+        if self.repository.is_revoked(RevocationKind::DeveloperKey, &signature.key_id).await? {
+            return Err(ModuleError::SignatureVerificationFailed(
+                format!("{} is signed with revoked developer key {}", name, signature.key_id)
+            ));
+        }
+        let developer_key = self.repository.get_developer_key(&signature.key_id).await?
+            .ok_or_else(|| ModuleError::SignatureVerificationFailed(
+                format!("{} is signed with unknown developer key {}", name, signature.key_id)
+            ))?;
+        if !verify_signature(&developer_key.public_key, &developer_payload(package), &signature.signature) {
+            return Err(ModuleError::SignatureVerificationFailed(
+                format!("Developer signature of {} is invalid", name)
+            ));
+        }
+
+        let countersignature = signature.countersignature.as_ref()
+            .ok_or_else(|| ModuleError::SignatureVerificationFailed(
+                format!("{} has not been countersigned by the marketplace", name)
+            ))?;
+        if self.repository.is_revoked(RevocationKind::MarketplaceKey, &countersignature.key_id).await? {
+            return Err(ModuleError::SignatureVerificationFailed(
+                format!("{} is countersigned with revoked marketplace key {}", name, countersignature.key_id)
+            ));
+        }
+        let marketplace_key = self.config.marketplace_keys.get(&countersignature.key_id)
+            .ok_or_else(|| ModuleError::SignatureVerificationFailed(
+                format!("{} is countersigned with untrusted marketplace key {}", name, countersignature.key_id)
+            ))?;
+        if !verify_signature(marketplace_key, &marketplace_payload(package, signature), &countersignature.signature) {
+            return Err(ModuleError::SignatureVerificationFailed(
+                format!("Marketplace countersignature of {} is invalid", name)
+            ));
+        }
+
+        Ok(())
+    }
+}
+
+/// Hex SHA-256 over the paths, content types and contents of package
+/// assets, independent of their order
+pub fn assets_checksum(assets: &[PackageAsset]) -> String {
+    let mut entries: Vec<String> = assets.iter()
+        .map(|asset| format!("{}\n{}\n{}\n", asset.path, asset.content_type, package_checksum(&asset.content)))
+        .collect();
+    entries.sort();
+    package_checksum(entries.concat().as_bytes())
+}
+
+/// Digest of the libraries a package declares it was built with, so they
+/// cannot be left out to get past the vulnerability lookup
+pub fn dependencies_checksum(dependencies: &[LockedDependency]) -> String {
+    let mut entries: Vec<String> = dependencies.iter()
+        .map(|dependency| format!("{}\n{}\n{}\n", dependency.ecosystem, dependency.name, dependency.version))
+        .collect();
+    entries.sort();
+    package_checksum(entries.concat().as_bytes())
+}
+
+fn developer_payload(package: &ModulePackage) -> Vec<u8> {
+    let mut payload = format!(
+        "adx-module-package:v1\n{}\n{}\n{}",
+        package.metadata.id,
+        package.metadata.version,
+        package.checksum.to_lowercase(),
+    );
+    // Packages without assets keep the payload they were signed with
+    // before assets existed
+    if !package.assets.is_empty() {
+        payload.push_str(&format!("\nassets:{}", assets_checksum(&package.assets)));
+    }
+    if !package.locked_dependencies.is_empty() {
+        payload.push_str(&format!("\ndependencies:{}", dependencies_checksum(&package.locked_dependencies)));
+    }
+    payload.into_bytes()
+}
+
+fn marketplace_payload(package: &ModulePackage, signature: &PackageSignature) -> Vec<u8> {
+    let mut payload = developer_payload(package);
+    payload.extend_from_slice(format!("\n{}\n{}", signature.key_id, signature.signature).as_bytes());
+    payload
+}
+
+fn verify_signature(public_key: &str, payload: &[u8], signature: &str) -> bool {
+    let (Ok(public_key), Ok(signature)) = (BASE64.decode(public_key), BASE64.decode(signature)) else {
+        return false;
+    };
+    UnparsedPublicKey::new(&ED25519, public_key)
+        .verify(payload, &signature)
+        .is_ok()
+}
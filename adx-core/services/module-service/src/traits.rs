@@ -1,13 +1,23 @@
 use async_trait::async_trait;
 use serde_json::Value;
 use std::collections::HashMap;
+use std::sync::Arc;
 use uuid::Uuid;
 
 use crate::{
     ModuleResult, ModuleMetadata, ModuleManifest, ModuleInstance, ModulePackage,
     ModuleSearchQuery, ModuleSearchResult, InstallModuleRequest, InstallModuleResult,
     UpdateModuleRequest, UpdateModuleResult, UninstallModuleRequest, UninstallModuleResult,
-    ResourceUsage, HealthStatus,
+    ResourceUsage, HealthStatus, CapabilityGrant, CapabilityGrants,
+    DeveloperKey, Revocation, RevocationKind,
+    events::{EventBus, EventDelivery},
+    settings::ModuleSettings,
+    metering::{ModuleUsage, UsageMeter},
+    rollout::{ModuleRollout, RolloutStatus},
+    assets::{FrontendBundle, ModuleAsset},
+    search::{IndexHit, MarketplaceListing},
+    security::{ReviewStatus, SecurityReport},
+    jobs::{JobRun, ModuleJob, ModuleJobScheduler},
 };
 
 /// Core trait that all ADX modules must implement
@@ -54,6 +64,22 @@ pub trait AdxModule: Send + Sync {
     
     /// Get module's extension points
     fn get_extension_points(&self) -> HashMap<String, Box<dyn ExtensionPoint>>;
+    
+    /// Bind the module to the host API of a tenant, limited to the
+    /// capabilities the tenant granted it
+    fn bind_host(&mut self, _host: ModuleHost) {}
+}
+
+/// What the host hands an installed module instance: its tenant, the
+/// capabilities the tenant granted it, the event bus and its jobs
+#[derive(Clone)]
+pub struct ModuleHost {
+    pub instance_id: Uuid,
+    pub tenant_id: String,
+    pub grants: CapabilityGrants,
+    pub events: Arc<EventBus>,
+    pub meter: Arc<UsageMeter>,
+    pub jobs: Arc<ModuleJobScheduler>,
 }
 
 /// Module status enumeration
@@ -79,6 +105,8 @@ pub enum ModuleEvent {
     ResourceLimitWarning { resource: String, usage: f64, limit: f64 },
     HealthCheckFailed { reason: String },
     Custom { event_type: String, data: Value },
+    /// A run of a background job the module registered
+    JobTriggered { job: String, run_id: Uuid, payload: Value },
 }
 
 /// Extension point trait for module extensibility
@@ -138,11 +166,196 @@ pub trait ModuleRepository: Send + Sync {
     /// List instances for tenant
     async fn list_tenant_instances(&self, tenant_id: &str) -> ModuleResult<Vec<ModuleInstance>>;
     
+    /// List instances of a module across tenants
+    async fn list_module_instances(&self, module_id: &str) -> ModuleResult<Vec<ModuleInstance>>;
+    
     /// Update instance status
     async fn update_instance_status(&self, instance_id: Uuid, status: ModuleStatus) -> ModuleResult<()>;
     
     /// Delete instance
     async fn delete_instance(&self, instance_id: Uuid) -> ModuleResult<()>;
+    
+    /// Save the capabilities a tenant granted a module
+    async fn save_capability_grant(&self, grant: &CapabilityGrant) -> ModuleResult<()>;
+    
+    /// Get the capabilities a tenant granted a module
+    async fn get_capability_grant(&self, module_id: &str, tenant_id: &str) -> ModuleResult<Option<CapabilityGrant>>;
+    
+    /// Delete a tenant's grant to a module
+    async fn delete_capability_grant(&self, module_id: &str, tenant_id: &str) -> ModuleResult<()>;
+    
+    /// Register a developer's signing key
+    async fn save_developer_key(&self, key: &DeveloperKey) -> ModuleResult<()>;
+    
+    /// Get a developer signing key by key id
+    async fn get_developer_key(&self, key_id: &str) -> ModuleResult<Option<DeveloperKey>>;
+    
+    /// Add an entry to the revocation list
+    async fn save_revocation(&self, revocation: &Revocation) -> ModuleResult<()>;
+    
+    /// List the revocation list
+    async fn list_revocations(&self) -> ModuleResult<Vec<Revocation>>;
+    
+    /// Whether a key or package is on the revocation list
+    async fn is_revoked(&self, kind: RevocationKind, value: &str) -> ModuleResult<bool>;
+    
+    /// Save a new rollout
+    async fn create_rollout(&self, rollout: &ModuleRollout) -> ModuleResult<()>;
+    
+    /// Get a rollout by ID
+    async fn get_rollout(&self, rollout_id: Uuid) -> ModuleResult<Option<ModuleRollout>>;
+    
+    /// List a module's rollouts, newest first
+    async fn list_module_rollouts(&self, module_id: &str) -> ModuleResult<Vec<ModuleRollout>>;
+    
+    /// List rollouts that are running or rolling back
+    async fn list_rollouts_in_progress(&self) -> ModuleResult<Vec<ModuleRollout>>;
+    
+    /// Save a rollout's stage and instances, leaving its status alone
+    async fn save_rollout_progress(&self, rollout: &ModuleRollout) -> ModuleResult<()>;
+    
+    /// Move a rollout from one status to another; false if it was not in `from`
+    async fn transition_rollout(
+        &self,
+        rollout_id: Uuid,
+        from: RolloutStatus,
+        to: RolloutStatus,
+        reason: Option<&str>,
+    ) -> ModuleResult<bool>;
+    
+    /// Queue event deliveries
+    async fn save_event_deliveries(&self, deliveries: &[EventDelivery]) -> ModuleResult<()>;
+    
+    /// List pending deliveries whose next attempt is due, oldest first
+    async fn due_event_deliveries(&self, limit: u32) -> ModuleResult<Vec<EventDelivery>>;
+    
+    /// Remove a delivery its subscriber handled
+    async fn mark_event_delivered(&self, delivery_id: Uuid) -> ModuleResult<()>;
+    
+    /// Record a failed attempt; dead-letter the delivery when `next_attempt_at` is `None`
+    async fn mark_event_delivery_failed(
+        &self,
+        delivery_id: Uuid,
+        attempts: u32,
+        error: &str,
+        next_attempt_at: Option<chrono::DateTime<chrono::Utc>>,
+    ) -> ModuleResult<()>;
+    
+    /// List a tenant's dead-lettered deliveries
+    async fn list_dead_lettered_deliveries(&self, tenant_id: &str) -> ModuleResult<Vec<EventDelivery>>;
+    
+    /// Requeue a tenant's dead-lettered delivery; false if there is none
+    async fn requeue_event_delivery(&self, tenant_id: &str, delivery_id: Uuid) -> ModuleResult<bool>;
+    
+    /// Save a new settings version and make it the instance's configuration;
+    /// false if the version already exists
+    async fn save_settings_version(&self, settings: &ModuleSettings) -> ModuleResult<bool>;
+    
+    /// Get the latest settings version of an instance
+    async fn get_latest_settings(&self, instance_id: Uuid) -> ModuleResult<Option<ModuleSettings>>;
+    
+    /// List the settings versions of an instance, newest first
+    async fn list_settings_versions(&self, instance_id: Uuid) -> ModuleResult<Vec<ModuleSettings>>;
+    
+    /// Add metered usage to an instance's hour
+    async fn add_module_usage(&self, usage: &ModuleUsage) -> ModuleResult<()>;
+    
+    /// List a tenant's hourly module usage from `from` until `to`
+    async fn list_tenant_module_usage(
+        &self,
+        tenant_id: &str,
+        from: chrono::DateTime<chrono::Utc>,
+        to: chrono::DateTime<chrono::Utc>,
+    ) -> ModuleResult<Vec<ModuleUsage>>;
+    
+    /// List hours of usage starting before `before` not reported for billing yet
+    async fn list_unreported_module_usage(&self, before: chrono::DateTime<chrono::Utc>) -> ModuleResult<Vec<ModuleUsage>>;
+    
+    /// Mark an hour of an instance's usage reported for billing
+    async fn mark_module_usage_reported(
+        &self,
+        instance_id: Uuid,
+        period_start: chrono::DateTime<chrono::Utc>,
+    ) -> ModuleResult<()>;
+    
+    /// Save the frontend assets of a module version, and its remote entry if
+    /// it has one; leaves a version that is already saved alone
+    async fn save_module_assets(&self, bundle: Option<&FrontendBundle>, assets: &[ModuleAsset]) -> ModuleResult<()>;
+    
+    /// Get a frontend asset of a module version, with its content
+    async fn get_module_asset(
+        &self,
+        module_id: &str,
+        version: &semver::Version,
+        path: &str,
+    ) -> ModuleResult<Option<ModuleAsset>>;
+    
+    /// Get the remote entry and micro-frontends of a module version
+    async fn get_frontend_bundle(&self, module_id: &str, version: &semver::Version) -> ModuleResult<Option<FrontendBundle>>;
+    
+    /// Add marketplace catalog listings to the search index, or refresh them
+    async fn upsert_marketplace_listings(&self, listings: &[MarketplaceListing]) -> ModuleResult<()>;
+    
+    /// Remove delisted modules from the search index
+    async fn delete_marketplace_listings(&self, module_ids: &[String]) -> ModuleResult<()>;
+    
+    /// Indexed listings matching a full-text query, best match first; every
+    /// listing, unranked, without one
+    async fn search_marketplace_index(&self, text: Option<&str>, limit: u32) -> ModuleResult<Vec<IndexHit>>;
+    
+    /// Modules installed by tenants other than `exclude_tenant` that have any
+    /// of `module_ids` installed, with how many such tenants installed each
+    async fn list_co_installed_modules(
+        &self,
+        module_ids: &[String],
+        exclude_tenant: &str,
+    ) -> ModuleResult<Vec<(String, u64)>>;
+    
+    /// Save a package's security report, or its review
+    async fn save_security_report(&self, report: &SecurityReport) -> ModuleResult<()>;
+    
+    async fn get_security_report(&self, scan_id: Uuid) -> ModuleResult<Option<SecurityReport>>;
+    
+    /// Get the latest report of a module version with this content
+    async fn find_security_report(
+        &self,
+        module_id: &str,
+        version: &semver::Version,
+        checksum: &str,
+    ) -> ModuleResult<Option<SecurityReport>>;
+    
+    /// List reports by review status, oldest first
+    async fn list_security_reports(&self, status: ReviewStatus) -> ModuleResult<Vec<SecurityReport>>;
+    
+    /// List the latest report of every scanned version of the modules
+    async fn list_module_security_reports(&self, module_ids: &[String]) -> ModuleResult<Vec<SecurityReport>>;
+    
+    /// Insert or update a module's background job
+    async fn save_module_job(&self, job: &ModuleJob) -> ModuleResult<()>;
+    
+    async fn get_module_job(&self, job_id: Uuid) -> ModuleResult<Option<ModuleJob>>;
+    
+    /// Find an instance's job by name
+    async fn find_module_job(&self, instance_id: Uuid, name: &str) -> ModuleResult<Option<ModuleJob>>;
+    
+    async fn list_instance_jobs(&self, instance_id: Uuid) -> ModuleResult<Vec<ModuleJob>>;
+    
+    async fn list_tenant_jobs(&self, tenant_id: &str) -> ModuleResult<Vec<ModuleJob>>;
+    
+    /// Delete a job with its runs
+    async fn delete_module_job(&self, job_id: Uuid) -> ModuleResult<()>;
+    
+    /// Insert or update a job run
+    async fn save_job_run(&self, run: &JobRun) -> ModuleResult<()>;
+    
+    /// A job's latest runs, newest first
+    async fn list_job_runs(&self, job_id: Uuid, limit: u32) -> ModuleResult<Vec<JobRun>>;
+    
+    /// Count an instance's runs still running that started after `since`
+    async fn count_running_job_runs(&self, instance_id: Uuid, since: chrono::DateTime<chrono::Utc>) -> ModuleResult<u32>;
+    
+    /// Delete all but a job's latest `keep` runs
+    async fn prune_job_runs(&self, job_id: Uuid, keep: u32) -> ModuleResult<()>;
 }
 
 /// Module marketplace trait
@@ -326,7 +539,7 @@ pub enum ScanStatus {
 }
 
 /// Security issue
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub struct SecurityIssue {
     pub id: String,
     pub severity: Severity,
@@ -339,7 +552,7 @@ pub struct SecurityIssue {
 }
 
 /// Issue severity
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, serde::Serialize, serde::Deserialize)]
 pub enum Severity {
     Critical,
     High,
@@ -349,7 +562,7 @@ pub enum Severity {
 }
 
 /// Issue category
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
 pub enum IssueCategory {
     Vulnerability,
     MaliciousCode,
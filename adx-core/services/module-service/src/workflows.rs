@@ -6,7 +6,9 @@ use semver::Version;
 use crate::{
     ModuleResult, ModuleError, InstallModuleRequest, InstallModuleResult,
     UpdateModuleRequest, UpdateModuleResult, UninstallModuleRequest, UninstallModuleResult,
-    ModulePackage, ModuleInstance, ModuleStatus, SecurityScanResult,
+    ModulePackage, ModuleInstance, ModuleStatus, ModuleManifest, SecurityScanResult,
+    HookPhase, HookContext,
+    rollout::{ModuleRollout, RolloutFailureAction, RolloutInstance, RolloutInstanceState, RolloutStatus},
 };
 
 // Temporal workflow implementations for module operations
@@ -55,6 +57,7 @@ pub async fn install_module_workflow(
                     user_id: request.user_id.clone(),
                     configuration: None,
                     auto_activate: false,
+                    consent: None,
                 },
             ).await?;
             installed_dependencies.push(dep_result.instance_id);
@@ -131,7 +134,43 @@ pub async fn install_module_workflow(
         e
     })?;
 
-    // Step 8: Initialize module
+    // Step 8: Run the module's install hook in its sandbox
+    let install_hook = LifecycleHookRequest {
+        manifest: package.manifest.clone(),
+        context: HookContext {
+            phase: HookPhase::Install,
+            instance_id: instance.id,
+            tenant_id: request.tenant_id.clone(),
+            module_id: request.module_id.clone(),
+            from_version: None,
+            to_version: Some(package.metadata.version.clone()),
+            cleanup_data: false,
+            compensating: false,
+        },
+    };
+    temporal_sdk::workflow::call_activity(
+        run_lifecycle_hook,
+        install_hook.clone(),
+    ).await.map_err(|e| {
+        // The hook compensated itself; rollback deployment and instance
+        temporal_sdk::workflow::spawn_activity(
+            cleanup_module_deployment,
+            CleanupDeploymentRequest {
+                instance_id: instance.id,
+                deployment_id: deployment.id.clone(),
+            },
+        );
+        temporal_sdk::workflow::spawn_activity(
+            cleanup_module_instance,
+            CleanupInstanceRequest {
+                instance_id: instance.id,
+                cleanup_data: true,
+            },
+        );
+        e
+    })?;
+
+    // Step 9: Initialize module
     let initialization = temporal_sdk::workflow::call_activity(
         initialize_module,
         InitializeModuleRequest {
@@ -139,7 +178,8 @@ pub async fn install_module_workflow(
             configuration: request.configuration.unwrap_or_default(),
         },
     ).await.map_err(|e| {
-        // Rollback deployment on initialization failure
+        // Undo the install hook and rollback deployment on initialization failure
+        temporal_sdk::workflow::spawn_activity(compensate_lifecycle_hook, install_hook.clone());
         temporal_sdk::workflow::spawn_activity(
             cleanup_module_deployment,
             CleanupDeploymentRequest {
@@ -150,26 +190,32 @@ pub async fn install_module_workflow(
         e
     })?;
 
-    // Step 9: Register module extensions
+    // Step 10: Register module extensions
     temporal_sdk::workflow::call_activity(
         register_module_extensions,
         RegisterExtensionsRequest {
             instance_id: instance.id,
             extensions: package.manifest.capabilities.clone(),
         },
-    ).await?;
+    ).await.map_err(|e| {
+        temporal_sdk::workflow::spawn_activity(compensate_lifecycle_hook, install_hook.clone());
+        e
+    })?;
 
-    // Step 10: Auto-activate if requested
+    // Step 11: Auto-activate if requested
     if request.auto_activate {
         temporal_sdk::workflow::call_activity(
             activate_module,
             ActivateModuleRequest {
                 instance_id: instance.id,
             },
-        ).await?;
+        ).await.map_err(|e| {
+            temporal_sdk::workflow::spawn_activity(compensate_lifecycle_hook, install_hook.clone());
+            e
+        })?;
     }
 
-    // Step 11: Start monitoring
+    // Step 12: Start monitoring
     temporal_sdk::workflow::call_activity(
         start_module_monitoring,
         StartMonitoringRequest {
@@ -177,7 +223,7 @@ pub async fn install_module_workflow(
         },
     ).await?;
 
-    // Step 12: Send installation notification
+    // Step 13: Send installation notification
     temporal_sdk::workflow::call_activity(
         send_installation_notification,
         InstallationNotificationRequest {
@@ -315,7 +361,38 @@ pub async fn update_module_workflow(
         e
     })?;
 
-    // Step 9: Update module instance record
+    // Step 9: Run the new version's upgrade hook in its sandbox
+    let upgrade_hook = LifecycleHookRequest {
+        manifest: new_package.manifest.clone(),
+        context: HookContext {
+            phase: HookPhase::Upgrade,
+            instance_id: request.instance_id,
+            tenant_id: current_instance.tenant_id.clone(),
+            module_id: current_instance.module_id.clone(),
+            from_version: Some(current_instance.version.clone()),
+            to_version: Some(target_version.clone()),
+            cleanup_data: false,
+            compensating: false,
+        },
+    };
+    temporal_sdk::workflow::call_activity(
+        run_lifecycle_hook,
+        upgrade_hook.clone(),
+    ).await.map_err(|e| {
+        // The hook compensated itself; restore from backup
+        if let Some(backup_id) = &backup_id {
+            temporal_sdk::workflow::spawn_activity(
+                restore_module_from_backup,
+                RestoreFromBackupRequest {
+                    instance_id: request.instance_id,
+                    backup_id: backup_id.clone(),
+                },
+            );
+        }
+        e
+    })?;
+
+    // Step 10: Update module instance record
     temporal_sdk::workflow::call_activity(
         update_module_instance,
         UpdateInstanceRequest {
@@ -323,9 +400,12 @@ pub async fn update_module_workflow(
             version: target_version.clone(),
             status: ModuleStatus::Installed,
         },
-    ).await?;
+    ).await.map_err(|e| {
+        temporal_sdk::workflow::spawn_activity(compensate_lifecycle_hook, upgrade_hook.clone());
+        e
+    })?;
 
-    // Step 10: Reactivate if it was active before
+    // Step 11: Reactivate if it was active before
     if was_active {
         temporal_sdk::workflow::call_activity(
             activate_module,
@@ -333,7 +413,8 @@ pub async fn update_module_workflow(
                 instance_id: request.instance_id,
             },
         ).await.map_err(|e| {
-            // Restore from backup on activation failure
+            // Undo the upgrade hook and restore from backup on activation failure
+            temporal_sdk::workflow::spawn_activity(compensate_lifecycle_hook, upgrade_hook.clone());
             if let Some(backup_id) = &backup_id {
                 temporal_sdk::workflow::spawn_activity(
                     restore_module_from_backup,
@@ -347,7 +428,7 @@ pub async fn update_module_workflow(
         })?;
     }
 
-    // Step 11: Send update notification
+    // Step 12: Send update notification
     temporal_sdk::workflow::call_activity(
         send_update_notification,
         UpdateNotificationRequest {
@@ -369,6 +450,118 @@ pub async fn update_module_workflow(
     })
 }
 
+/// Staged rollout of a module update across tenants: each stage updates its
+/// share of instances with `update_module_workflow`, soaks, and checks their
+/// health before the next, pausing or rolling back on a breach
+#[temporal_sdk::workflow]
+pub async fn module_rollout_workflow(
+    request: ModuleRolloutRequest,
+) -> Result<RolloutStatus, ModuleWorkflowError> {
+    tracing::info!("Starting module rollout workflow for: {}", request.rollout_id);
+
+    let mut rollout = temporal_sdk::workflow::call_activity(
+        get_module_rollout,
+        GetRolloutRequest {
+            rollout_id: request.rollout_id,
+        },
+    ).await?;
+
+    while let Some(stage) = rollout.policy.stages.get(rollout.next_stage).cloned() {
+        // Step 1: Update the stage's instances
+        let stage_index = rollout.next_stage;
+        for instance in rollout.instances.iter_mut()
+            .filter(|instance| instance.stage == stage_index && instance.state == RolloutInstanceState::Pending)
+        {
+            let update = temporal_sdk::workflow::call_child_workflow(
+                update_module_workflow,
+                UpdateModuleRequest {
+                    instance_id: instance.instance_id,
+                    target_version: Some(rollout.target_version.clone()),
+                    preserve_config: true,
+                    backup_current: true,
+                    consent: None,
+                },
+            ).await;
+
+            instance.state = match update {
+                Ok(_) => RolloutInstanceState::Updated,
+                Err(e) => {
+                    instance.error = Some(e.to_string());
+                    RolloutInstanceState::Failed
+                }
+            };
+        }
+
+        // Step 2: Let the updated instances soak
+        temporal_sdk::workflow::sleep(std::time::Duration::from_secs(stage.soak_seconds)).await;
+
+        // Step 3: Check their health against the policy's thresholds
+        let health = temporal_sdk::workflow::call_activity(
+            collect_rollout_health,
+            CollectRolloutHealthRequest {
+                instances: rollout.instances.clone(),
+            },
+        ).await?;
+
+        if let Some(reason) = health.breach(&rollout.policy.thresholds) {
+            let status = match rollout.policy.on_failure {
+                RolloutFailureAction::Pause => RolloutStatus::Paused,
+                RolloutFailureAction::Rollback => {
+                    for instance in rollout.instances.iter_mut()
+                        .filter(|instance| instance.state == RolloutInstanceState::Updated)
+                    {
+                        temporal_sdk::workflow::call_child_workflow(
+                            update_module_workflow,
+                            UpdateModuleRequest {
+                                instance_id: instance.instance_id,
+                                target_version: Some(instance.previous_version.clone()),
+                                preserve_config: true,
+                                backup_current: false,
+                                consent: None,
+                            },
+                        ).await?;
+                        instance.state = RolloutInstanceState::RolledBack;
+                    }
+                    RolloutStatus::RolledBack
+                }
+            };
+
+            temporal_sdk::workflow::call_activity(
+                save_module_rollout,
+                SaveRolloutRequest {
+                    rollout: rollout.clone(),
+                    status,
+                    reason: Some(reason),
+                },
+            ).await?;
+            return Ok(status);
+        }
+
+        // Step 4: Record progress before the next stage
+        rollout.next_stage += 1;
+        temporal_sdk::workflow::call_activity(
+            save_module_rollout,
+            SaveRolloutRequest {
+                rollout: rollout.clone(),
+                status: RolloutStatus::Running,
+                reason: None,
+            },
+        ).await?;
+    }
+
+    temporal_sdk::workflow::call_activity(
+        save_module_rollout,
+        SaveRolloutRequest {
+            rollout: rollout.clone(),
+            status: RolloutStatus::Completed,
+            reason: None,
+        },
+    ).await?;
+
+    tracing::info!("Successfully completed module rollout workflow for: {}", request.rollout_id);
+    Ok(RolloutStatus::Completed)
+}
+
 /// Module uninstallation workflow with cleanup
 #[temporal_sdk::workflow]
 pub async fn uninstall_module_workflow(
@@ -420,7 +613,45 @@ pub async fn uninstall_module_workflow(
         ).await?;
     }
 
-    // Step 5: Unregister module extensions
+    // Step 5: Run the module's uninstall hook in its sandbox
+    let installed_package = temporal_sdk::workflow::call_activity(
+        download_module_package,
+        DownloadPackageRequest {
+            module_id: instance.module_id.clone(),
+            version: Some(instance.version.clone()),
+            tenant_id: instance.tenant_id.clone(),
+        },
+    ).await?;
+    let uninstall_hook = LifecycleHookRequest {
+        manifest: installed_package.manifest,
+        context: HookContext {
+            phase: HookPhase::Uninstall,
+            instance_id: request.instance_id,
+            tenant_id: instance.tenant_id.clone(),
+            module_id: instance.module_id.clone(),
+            from_version: Some(instance.version.clone()),
+            to_version: None,
+            cleanup_data: request.cleanup_data,
+            compensating: false,
+        },
+    };
+    temporal_sdk::workflow::call_activity(
+        run_lifecycle_hook,
+        uninstall_hook.clone(),
+    ).await.map_err(|e| {
+        // The hook compensated itself; reactivate the module if it was active
+        if matches!(instance.status, ModuleStatus::Active) {
+            temporal_sdk::workflow::spawn_activity(
+                activate_module,
+                ActivateModuleRequest {
+                    instance_id: request.instance_id,
+                },
+            );
+        }
+        e
+    })?;
+
+    // Step 6: Unregister module extensions
     temporal_sdk::workflow::call_activity(
         unregister_module_extensions,
         UnregisterExtensionsRequest {
@@ -428,7 +659,7 @@ pub async fn uninstall_module_workflow(
         },
     ).await?;
 
-    // Step 6: Stop monitoring
+    // Step 7: Stop monitoring
     temporal_sdk::workflow::call_activity(
         stop_module_monitoring,
         StopMonitoringRequest {
@@ -436,16 +667,19 @@ pub async fn uninstall_module_workflow(
         },
     ).await?;
 
-    // Step 7: Cleanup module resources
+    // Step 8: Cleanup module resources
     let cleanup_summary = temporal_sdk::workflow::call_activity(
         cleanup_module_resources,
         CleanupResourcesRequest {
             instance_id: request.instance_id,
             cleanup_data: request.cleanup_data,
         },
-    ).await?;
+    ).await.map_err(|e| {
+        temporal_sdk::workflow::spawn_activity(compensate_lifecycle_hook, uninstall_hook.clone());
+        e
+    })?;
 
-    // Step 8: Remove module deployment
+    // Step 9: Remove module deployment
     temporal_sdk::workflow::call_activity(
         remove_module_deployment,
         RemoveDeploymentRequest {
@@ -453,7 +687,7 @@ pub async fn uninstall_module_workflow(
         },
     ).await?;
 
-    // Step 9: Delete module instance record
+    // Step 10: Delete module instance record
     temporal_sdk::workflow::call_activity(
         delete_module_instance,
         DeleteInstanceRequest {
@@ -461,7 +695,7 @@ pub async fn uninstall_module_workflow(
         },
     ).await?;
 
-    // Step 10: Send uninstallation notification
+    // Step 11: Send uninstallation notification
     temporal_sdk::workflow::call_activity(
         send_uninstallation_notification,
         UninstallationNotificationRequest {
@@ -707,11 +941,39 @@ pub struct InstallationNotificationRequest {
     pub status: String,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LifecycleHookRequest {
+    pub manifest: ModuleManifest,
+    pub context: HookContext,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RollbackDependenciesRequest {
     pub instance_ids: Vec<Uuid>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleRolloutRequest {
+    pub rollout_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GetRolloutRequest {
+    pub rollout_id: Uuid,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CollectRolloutHealthRequest {
+    pub instances: Vec<RolloutInstance>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveRolloutRequest {
+    pub rollout: ModuleRollout,
+    pub status: RolloutStatus,
+    pub reason: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct MarketplaceSyncResult {
     pub modules_synced: u32,
@@ -0,0 +1,16 @@
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    // A PROTOC set in the environment still wins over the vendored binary
+    if std::env::var_os("PROTOC").is_none() {
+        std::env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    }
+    println!("cargo:rerun-if-env-changed=PROTOC");
+
+    // Services and clients for every internal API; each service enables the
+    // side it needs through the generated modules
+    tonic_build::configure().compile(
+        &["proto/adx/quota/v1/quota.proto", "proto/adx/tenant/v1/permissions.proto"],
+        &["proto"],
+    )?;
+    println!("cargo:rerun-if-changed=proto");
+    Ok(())
+}
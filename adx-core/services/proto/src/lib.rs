@@ -0,0 +1,16 @@
+// ADX Core internal gRPC APIs
+// Protobuf definitions for hot service-to-service paths, generated into
+// tonic clients and servers. REST stays the public contract; these are only
+// served on the services' internal gRPC ports.
+
+pub mod quota {
+    pub mod v1 {
+        tonic::include_proto!("adx.quota.v1");
+    }
+}
+
+pub mod tenant {
+    pub mod v1 {
+        tonic::include_proto!("adx.tenant.v1");
+    }
+}
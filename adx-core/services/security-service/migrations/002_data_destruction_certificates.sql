@@ -0,0 +1,31 @@
+-- Data destruction certificates
+-- Signed evidence that a terminated tenant's data was purged from every service.
+-- Certificates are append-only; a correction is a new certificate.
+
+CREATE TABLE data_destruction_certificates (
+    id UUID PRIMARY KEY,
+    tenant_id VARCHAR(255) NOT NULL,
+    tenant_name VARCHAR(255) NOT NULL,
+    requested_by VARCHAR(255) NOT NULL,
+    soft_deleted_at TIMESTAMPTZ NOT NULL,
+    purged_at TIMESTAMPTZ NOT NULL,
+    purged_services JSONB NOT NULL DEFAULT '[]',
+    digest VARCHAR(64) NOT NULL,
+    signature VARCHAR(128) NOT NULL,
+    signing_key_id VARCHAR(64) NOT NULL,
+    issued_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Indexes for destruction certificates
+CREATE INDEX idx_data_destruction_certificates_tenant_id ON data_destruction_certificates(tenant_id);
+CREATE INDEX idx_data_destruction_certificates_issued_at ON data_destruction_certificates(issued_at);
+
+-- Reject updates and deletes so issued certificates cannot be altered
+CREATE OR REPLACE FUNCTION prevent_destruction_certificate_changes()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'Data destruction certificates are immutable';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER data_destruction_certificates_immutable BEFORE UPDATE OR DELETE ON data_destruction_certificates FOR EACH ROW EXECUTE FUNCTION prevent_destruction_certificate_changes();
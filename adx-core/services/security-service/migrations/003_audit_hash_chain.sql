@@ -0,0 +1,58 @@
+-- Platform audit log
+-- Events from every service land in audit_logs. Each tenant's events form a
+-- hash chain: an event's hash covers its fields and the previous event's
+-- hash, so altering or removing a stored event breaks every later link.
+
+ALTER TABLE audit_logs ADD COLUMN service VARCHAR(100) NOT NULL DEFAULT 'security-service';
+ALTER TABLE audit_logs ADD COLUMN sequence BIGINT;
+ALTER TABLE audit_logs ADD COLUMN previous_hash VARCHAR(64);
+ALTER TABLE audit_logs ADD COLUMN hash VARCHAR(64);
+
+-- Events logged before chaining keep a NULL sequence and are not verified
+CREATE UNIQUE INDEX idx_audit_logs_tenant_sequence ON audit_logs(tenant_id, sequence);
+CREATE INDEX idx_audit_logs_service ON audit_logs(service);
+CREATE INDEX idx_audit_logs_action ON audit_logs(action);
+
+-- Latest link of each tenant's chain; locked while events are appended
+CREATE TABLE audit_chain_heads (
+    tenant_id VARCHAR(255) PRIMARY KEY,
+    sequence BIGINT NOT NULL,
+    hash VARCHAR(64) NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- How long each tenant's audit events are kept, where it differs from the
+-- service default
+CREATE TABLE audit_retention_policies (
+    tenant_id VARCHAR(255) PRIMARY KEY,
+    retention_days INTEGER NOT NULL,
+    updated_by VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT chk_audit_retention_days CHECK (retention_days > 0)
+);
+
+-- Where retention cut a tenant's chain. Verification starts from the last
+-- removed link, so purging old events does not read as tampering.
+CREATE TABLE audit_retention_runs (
+    id UUID PRIMARY KEY,
+    tenant_id VARCHAR(255) NOT NULL,
+    cutoff TIMESTAMPTZ NOT NULL,
+    deleted_count BIGINT NOT NULL,
+    last_deleted_sequence BIGINT,
+    last_deleted_hash VARCHAR(64),
+    ran_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_audit_retention_runs_tenant ON audit_retention_runs(tenant_id, ran_at DESC);
+
+-- Stored events are never changed; retention is the only way they leave
+CREATE OR REPLACE FUNCTION prevent_audit_log_changes()
+RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'Audit log events are immutable';
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER audit_logs_immutable BEFORE UPDATE ON audit_logs FOR EACH ROW EXECUTE FUNCTION prevent_audit_log_changes();
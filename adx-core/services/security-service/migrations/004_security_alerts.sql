@@ -0,0 +1,62 @@
+-- Security alerts
+-- Raised by the detection engine from audit events. While an alert is open,
+-- further matches for the same subject are added to it rather than raising
+-- new alerts.
+
+CREATE TYPE alert_rule AS ENUM (
+    'massdownload',
+    'privilegeescalation',
+    'failedloginburst',
+    'newloginlocation'
+);
+
+CREATE TYPE alert_status AS ENUM (
+    'open',
+    'acknowledged',
+    'resolved',
+    'falsepositive'
+);
+
+CREATE TABLE security_alerts (
+    id UUID PRIMARY KEY,
+    tenant_id VARCHAR(255) NOT NULL,
+    rule alert_rule NOT NULL,
+    severity security_event_severity NOT NULL,
+    status alert_status NOT NULL DEFAULT 'open',
+    title VARCHAR(255) NOT NULL,
+    description TEXT NOT NULL,
+    -- Identifies the subject of the alert, e.g. the user downloading
+    dedup_key VARCHAR(512) NOT NULL,
+    user_id VARCHAR(255),
+    -- Audit events that matched, most recent last
+    event_ids JSONB NOT NULL DEFAULT '[]',
+    event_count INTEGER NOT NULL DEFAULT 1,
+    details JSONB NOT NULL DEFAULT '{}',
+    first_seen_at TIMESTAMPTZ NOT NULL,
+    last_seen_at TIMESTAMPTZ NOT NULL,
+    assigned_to VARCHAR(255),
+    assigned_at TIMESTAMPTZ,
+    acknowledged_by VARCHAR(255),
+    acknowledged_at TIMESTAMPTZ,
+    resolved_by VARCHAR(255),
+    resolved_at TIMESTAMPTZ,
+    resolution_notes TEXT,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_security_alerts_tenant_status ON security_alerts(tenant_id, status);
+CREATE INDEX idx_security_alerts_severity ON security_alerts(severity);
+CREATE INDEX idx_security_alerts_assigned_to ON security_alerts(assigned_to);
+CREATE INDEX idx_security_alerts_created_at ON security_alerts(created_at);
+
+-- At most one alert per subject is open at a time
+CREATE UNIQUE INDEX idx_security_alerts_open_dedup ON security_alerts(tenant_id, dedup_key)
+    WHERE status IN ('open', 'acknowledged');
+
+-- Last audit event of each tenant's chain the detection engine has evaluated
+CREATE TABLE detection_checkpoints (
+    tenant_id VARCHAR(255) PRIMARY KEY,
+    last_sequence BIGINT NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,14 @@
+-- Compliance report evidence
+-- Reports are generated from a framework template and keep the evidence
+-- they were assessed on, so an auditor can download them as issued.
+-- Platform reports cover every tenant and are stored under tenant 'platform'.
+
+ALTER TABLE compliance_reports ADD COLUMN scope VARCHAR(20) NOT NULL DEFAULT 'tenant';
+ALTER TABLE compliance_reports ADD COLUMN template_version VARCHAR(20);
+ALTER TABLE compliance_reports ADD COLUMN compliance_score REAL;
+ALTER TABLE compliance_reports ADD COLUMN controls JSONB NOT NULL DEFAULT '[]';
+ALTER TABLE compliance_reports ADD COLUMN evidence JSONB NOT NULL DEFAULT '[]';
+
+ALTER TABLE compliance_reports ADD CONSTRAINT chk_compliance_reports_scope CHECK (scope IN ('tenant', 'platform'));
+
+CREATE INDEX idx_compliance_reports_scope ON compliance_reports(scope, created_at DESC);
@@ -0,0 +1,58 @@
+-- Key lifecycle
+-- Versioned JWT signing keys, tenant data keys and webhook signing secrets.
+-- A rotation stages a pending key that validators pick up, activates it once
+-- it has propagated, and keeps the key it replaced valid until retire_after
+-- so nothing signed or encrypted with it stops working mid-rotation.
+
+CREATE TYPE key_purpose AS ENUM (
+    'jwtsigning',
+    'tenantdata',
+    'webhooksigning'
+);
+
+CREATE TYPE key_status AS ENUM (
+    'pending',
+    'active',
+    'retiring',
+    'retired'
+);
+
+CREATE TABLE managed_keys (
+    id UUID PRIMARY KEY,
+    purpose key_purpose NOT NULL,
+    -- 'platform' for JWT signing keys, the tenant for data keys, the webhook for signing secrets
+    owner_id VARCHAR(255) NOT NULL,
+    kid VARCHAR(64) NOT NULL UNIQUE,
+    status key_status NOT NULL DEFAULT 'pending',
+    -- Key material encrypted with the master key
+    material BYTEA NOT NULL,
+    rotated_by VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    activated_at TIMESTAMPTZ,
+    retiring_at TIMESTAMPTZ,
+    -- End of the overlap window in which a replaced key is still accepted
+    retire_after TIMESTAMPTZ,
+    retired_at TIMESTAMPTZ
+);
+
+CREATE INDEX idx_managed_keys_owner ON managed_keys(purpose, owner_id, status);
+CREATE INDEX idx_managed_keys_retire_after ON managed_keys(retire_after) WHERE status = 'retiring';
+
+-- One active and at most one pending key per owner
+CREATE UNIQUE INDEX idx_managed_keys_one_active ON managed_keys(purpose, owner_id) WHERE status = 'active';
+CREATE UNIQUE INDEX idx_managed_keys_one_pending ON managed_keys(purpose, owner_id) WHERE status = 'pending';
+
+CREATE TABLE key_rotation_schedules (
+    purpose key_purpose NOT NULL,
+    owner_id VARCHAR(255) NOT NULL,
+    rotation_days INTEGER NOT NULL,
+    overlap_hours INTEGER NOT NULL,
+    enabled BOOLEAN NOT NULL DEFAULT true,
+    last_rotated_at TIMESTAMPTZ,
+    next_rotation_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (purpose, owner_id)
+);
+
+CREATE INDEX idx_key_rotation_schedules_due ON key_rotation_schedules(next_rotation_at) WHERE enabled;
@@ -0,0 +1,13 @@
+-- DLP policies
+-- One policy per tenant. Rules are kept as the JSON array services compile
+-- into scanners (see adx_shared::dlp::DlpRule); incidents are audit events
+-- with action 'dlp_incident' and need no table of their own.
+
+CREATE TABLE dlp_policies (
+    tenant_id VARCHAR(255) PRIMARY KEY,
+    enabled BOOLEAN NOT NULL DEFAULT true,
+    rules JSONB NOT NULL DEFAULT '[]',
+    updated_by VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
@@ -0,0 +1,27 @@
+-- Tenant network policies
+-- Where a tenant's users may connect from, enforced by api-gateway. Requests
+-- it refuses are audit events with action 'network_access_denied'.
+
+CREATE TABLE tenant_network_policies (
+    tenant_id VARCHAR(255) PRIMARY KEY,
+    enabled BOOLEAN NOT NULL DEFAULT true,
+    allowed_cidrs TEXT[] NOT NULL DEFAULT '{}',
+    -- ISO 3166-1 alpha-2 codes, upper case
+    allowed_countries TEXT[] NOT NULL DEFAULT '{}',
+    blocked_countries TEXT[] NOT NULL DEFAULT '{}',
+    block_tor BOOLEAN NOT NULL DEFAULT false,
+    block_vpn BOOLEAN NOT NULL DEFAULT false,
+    updated_by VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- TOR exit nodes and VPN provider ranges, replaced a category at a time from
+-- the platform's threat feeds
+CREATE TABLE anonymizer_ranges (
+    cidr VARCHAR(64) NOT NULL,
+    category VARCHAR(16) NOT NULL CHECK (category IN ('tor', 'vpn')),
+    source VARCHAR(255) NOT NULL,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (category, cidr)
+);
@@ -0,0 +1,83 @@
+-- Access review campaigns
+-- A campaign snapshots who holds what in a tenant (user roles and module
+-- capability grants) as review items. Reviewers attest to each item before
+-- the deadline; when auto_revoke is set, items nobody attested to are
+-- revoked when the campaign closes. The closed campaign's evidence is kept
+-- with a hash so it can be handed to auditors unchanged.
+
+CREATE TYPE access_review_status AS ENUM (
+    'open',
+    'completed',
+    'cancelled'
+);
+
+CREATE TYPE access_review_subject AS ENUM (
+    'userrole',
+    'modulegrant'
+);
+
+CREATE TYPE access_review_decision AS ENUM (
+    'pending',
+    'approved',
+    'revoked'
+);
+
+CREATE TABLE access_review_campaigns (
+    id UUID PRIMARY KEY,
+    tenant_id VARCHAR(255) NOT NULL,
+    name VARCHAR(255) NOT NULL,
+    status access_review_status NOT NULL DEFAULT 'open',
+    deadline TIMESTAMPTZ NOT NULL,
+    auto_revoke BOOLEAN NOT NULL DEFAULT true,
+    created_by VARCHAR(255) NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    -- Archived on completion; evidence_hash is the SHA-256 of the evidence as stored
+    evidence JSONB,
+    evidence_hash VARCHAR(64)
+);
+
+CREATE INDEX idx_access_review_campaigns_tenant ON access_review_campaigns(tenant_id, created_at DESC);
+CREATE INDEX idx_access_review_campaigns_deadline ON access_review_campaigns(deadline) WHERE status = 'open';
+
+-- One open campaign per tenant at a time
+CREATE UNIQUE INDEX idx_access_review_campaigns_one_open ON access_review_campaigns(tenant_id) WHERE status = 'open';
+
+CREATE TABLE access_review_items (
+    id UUID PRIMARY KEY,
+    campaign_id UUID NOT NULL REFERENCES access_review_campaigns(id) ON DELETE CASCADE,
+    tenant_id VARCHAR(255) NOT NULL,
+    subject_type access_review_subject NOT NULL,
+    -- The user, or the module instance holding the grant
+    subject_id VARCHAR(255) NOT NULL,
+    -- The user's email, or the module id
+    subject_name VARCHAR(255) NOT NULL,
+    -- The role, or the granted module version
+    entitlement VARCHAR(255) NOT NULL,
+    details JSONB NOT NULL DEFAULT '{}',
+    decision access_review_decision NOT NULL DEFAULT 'pending',
+    decided_by VARCHAR(255),
+    decided_at TIMESTAMPTZ,
+    comment TEXT,
+    revoked_at TIMESTAMPTZ,
+    -- Revoked because nobody attested to it before the deadline
+    auto_revoked BOOLEAN NOT NULL DEFAULT false,
+    revocation_error TEXT
+);
+
+CREATE INDEX idx_access_review_items_campaign ON access_review_items(campaign_id, decision);
+
+CREATE TABLE access_review_schedules (
+    tenant_id VARCHAR(255) PRIMARY KEY,
+    interval_days INTEGER NOT NULL,
+    -- How long reviewers have to attest once a campaign starts
+    review_days INTEGER NOT NULL,
+    auto_revoke BOOLEAN NOT NULL DEFAULT true,
+    enabled BOOLEAN NOT NULL DEFAULT true,
+    last_campaign_at TIMESTAMPTZ,
+    next_campaign_at TIMESTAMPTZ NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_access_review_schedules_due ON access_review_schedules(next_campaign_at) WHERE enabled;
@@ -0,0 +1,87 @@
+-- Security incidents
+-- An incident is opened from one or more security alerts and tracks the
+-- response: containment actions taken (some only after a second person
+-- approves them), a timeline of everything that happened, and the
+-- postmortem written once it is resolved.
+
+CREATE TYPE incident_status AS ENUM (
+    'open',
+    'contained',
+    'resolved',
+    'closed'
+);
+
+CREATE TYPE containment_action_type AS ENUM (
+    'revokesessions',
+    'disableapikeys',
+    'quarantinemodule',
+    'locktenant'
+);
+
+CREATE TYPE containment_action_status AS ENUM (
+    'pendingapproval',
+    'approved',
+    'rejected',
+    'expired',
+    'executing',
+    'completed',
+    'failed'
+);
+
+CREATE TABLE security_incidents (
+    id UUID PRIMARY KEY,
+    tenant_id VARCHAR(255) NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    description TEXT NOT NULL,
+    severity security_event_severity NOT NULL,
+    status incident_status NOT NULL DEFAULT 'open',
+    alert_ids UUID[] NOT NULL DEFAULT '{}',
+    opened_by VARCHAR(255) NOT NULL,
+    assigned_to VARCHAR(255),
+    opened_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    contained_at TIMESTAMPTZ,
+    resolved_at TIMESTAMPTZ,
+    closed_at TIMESTAMPTZ,
+    resolution_notes TEXT,
+    postmortem JSONB,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_security_incidents_tenant_status ON security_incidents(tenant_id, status);
+CREATE INDEX idx_security_incidents_alerts ON security_incidents USING GIN(alert_ids);
+
+CREATE TABLE incident_containment_actions (
+    id UUID PRIMARY KEY,
+    incident_id UUID NOT NULL REFERENCES security_incidents(id) ON DELETE CASCADE,
+    tenant_id VARCHAR(255) NOT NULL,
+    action_type containment_action_type NOT NULL,
+    -- The user, module instance or tenant the action is taken against
+    target VARCHAR(255) NOT NULL,
+    reason TEXT NOT NULL,
+    requires_approval BOOLEAN NOT NULL,
+    status containment_action_status NOT NULL,
+    requested_by VARCHAR(255) NOT NULL,
+    requested_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    decided_by VARCHAR(255),
+    decided_at TIMESTAMPTZ,
+    decision_comment TEXT,
+    executed_at TIMESTAMPTZ,
+    result JSONB,
+    error TEXT
+);
+
+CREATE INDEX idx_incident_containment_actions_incident ON incident_containment_actions(incident_id, requested_at);
+
+CREATE TABLE incident_timeline (
+    id UUID PRIMARY KEY,
+    incident_id UUID NOT NULL REFERENCES security_incidents(id) ON DELETE CASCADE,
+    tenant_id VARCHAR(255) NOT NULL,
+    -- e.g. opened, note, action_requested, action_completed, status_changed
+    entry_type VARCHAR(64) NOT NULL,
+    actor VARCHAR(255) NOT NULL,
+    message TEXT NOT NULL,
+    details JSONB NOT NULL DEFAULT '{}',
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_incident_timeline_incident ON incident_timeline(incident_id, occurred_at);
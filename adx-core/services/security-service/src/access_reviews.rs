@@ -0,0 +1,652 @@
+use crate::{
+    audit::AuditService,
+    config::AccessReviewConfig,
+    error::{SecurityError, SecurityResult},
+    models::{
+        AccessReviewCampaign, AccessReviewDecision, AccessReviewItem, AccessReviewMaintenanceSummary,
+        AccessReviewSchedule, AccessReviewStatus, AccessReviewSubject, AccessReviewSummary, AttestAccessRequest,
+        AuditOutcome, SetAccessReviewScheduleRequest, StartAccessReviewRequest,
+    },
+    repositories::AccessReviewRepository,
+};
+use chrono::{DateTime, Duration, Utc};
+use ring::digest;
+use serde::{de::DeserializeOwned, Deserialize};
+use serde_json::{json, Value};
+use std::{collections::HashMap, sync::Arc};
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+// Access reviews. A campaign snapshots every role a tenant's users hold and
+// every capability grant of its active modules; reviewers approve or revoke
+// each one before the deadline. When the campaign closes, access nobody
+// attested to is revoked (unless the campaign keeps it) and the outcome is
+// archived as evidence for compliance reports.
+
+pub const MAX_REVIEW_DAYS: i32 = 90;
+pub const MAX_INTERVAL_DAYS: i32 = 365;
+pub const MAX_CAMPAIGNS_LISTED: i64 = 100;
+
+/// Who starts and closes the campaigns the schedules ask for
+const SCHEDULER: &str = "access-review-scheduler";
+/// Users are listed from user-service a page at a time
+const USER_PAGE_SIZE: usize = 100;
+
+/// Envelope user-service and module-service wrap their responses in
+#[derive(Debug, Deserialize)]
+struct ServiceResponse<T> {
+    #[serde(default)]
+    success: bool,
+    data: Option<T>,
+    error: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct DirectoryUser {
+    id: String,
+    email: String,
+    #[serde(default)]
+    roles: Vec<String>,
+    #[serde(default)]
+    status: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct ModuleInstance {
+    id: String,
+    module_id: String,
+    version: String,
+    status: Value,
+}
+
+#[derive(Debug, Deserialize)]
+struct CapabilityGrant {
+    version: String,
+    capabilities: Value,
+    granted_by: String,
+    granted_at: DateTime<Utc>,
+}
+
+pub struct AccessReviewService {
+    repository: Arc<AccessReviewRepository>,
+    audit_service: Arc<AuditService>,
+    http_client: reqwest::Client,
+    config: AccessReviewConfig,
+}
+
+impl AccessReviewService {
+    pub fn new(
+        repository: Arc<AccessReviewRepository>,
+        audit_service: Arc<AuditService>,
+        config: AccessReviewConfig,
+    ) -> Self {
+        Self {
+            repository,
+            audit_service,
+            http_client: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    /// Start a campaign over the tenant's current access. A tenant has at
+    /// most one open campaign.
+    pub async fn start_campaign(
+        &self,
+        tenant_id: &str,
+        request: StartAccessReviewRequest,
+    ) -> SecurityResult<AccessReviewSummary> {
+        if request.created_by.trim().is_empty() {
+            return Err(SecurityError::Validation("created_by is required".to_string()));
+        }
+        let review_days = request.review_days.unwrap_or(self.config.default_review_days);
+        validate_review_days(review_days)?;
+
+        let now = Utc::now();
+        let campaign = AccessReviewCampaign {
+            id: Uuid::new_v4(),
+            tenant_id: tenant_id.to_string(),
+            name: request
+                .name
+                .filter(|name| !name.trim().is_empty())
+                .unwrap_or_else(|| format!("Access review {}", now.format("%Y-%m-%d"))),
+            status: AccessReviewStatus::Open,
+            deadline: now + Duration::days(review_days as i64),
+            auto_revoke: request.auto_revoke.unwrap_or(true),
+            created_by: request.created_by,
+            created_at: now,
+            completed_at: None,
+            evidence: None,
+            evidence_hash: None,
+        };
+
+        let items = self.collect_items(&campaign).await?;
+        self.repository.create_campaign(&campaign, &items).await?;
+
+        info!(tenant_id = %tenant_id, campaign_id = %campaign.id, items = items.len(), "Started access review");
+        self.audit_service.log_compliance_event(
+            tenant_id,
+            "access_review",
+            "start_access_review",
+            AuditOutcome::Success,
+            json!({
+                "campaign_id": campaign.id,
+                "name": campaign.name,
+                "deadline": campaign.deadline,
+                "auto_revoke": campaign.auto_revoke,
+                "items": items.len(),
+                "created_by": campaign.created_by,
+            }),
+        ).await?;
+
+        self.summary(campaign).await
+    }
+
+    /// Record reviewers' decisions. Revoked access is removed straight away.
+    pub async fn attest(
+        &self,
+        tenant_id: &str,
+        campaign_id: Uuid,
+        request: AttestAccessRequest,
+    ) -> SecurityResult<Vec<AccessReviewItem>> {
+        let campaign = self.open_campaign(tenant_id, campaign_id).await?;
+        if campaign.deadline <= Utc::now() {
+            return Err(SecurityError::Conflict(format!("Access review {} is past its deadline", campaign_id)));
+        }
+        if request.reviewer.trim().is_empty() {
+            return Err(SecurityError::Validation("reviewer is required".to_string()));
+        }
+        if request.attestations.is_empty() {
+            return Err(SecurityError::Validation("At least one attestation is required".to_string()));
+        }
+
+        // Check the whole batch before recording any of it
+        let pending: HashMap<Uuid, AccessReviewItem> = self
+            .repository
+            .list_items(campaign_id, Some(AccessReviewDecision::Pending))
+            .await?
+            .into_iter()
+            .map(|item| (item.id, item))
+            .collect();
+        for attestation in &request.attestations {
+            if attestation.decision == AccessReviewDecision::Pending {
+                return Err(SecurityError::Validation("An attestation must approve or revoke".to_string()));
+            }
+            let item = pending.get(&attestation.item_id).ok_or_else(|| {
+                SecurityError::Conflict(format!("Item {} is not awaiting review in this campaign", attestation.item_id))
+            })?;
+            if item.subject_type == AccessReviewSubject::UserRole && item.subject_id == request.reviewer {
+                return Err(SecurityError::Authorization("Reviewers cannot attest to their own access".to_string()));
+            }
+        }
+
+        let mut decided = Vec::with_capacity(request.attestations.len());
+        for attestation in &request.attestations {
+            let item = match self
+                .repository
+                .decide_item(
+                    campaign_id,
+                    attestation.item_id,
+                    attestation.decision,
+                    &request.reviewer,
+                    attestation.comment.as_deref(),
+                )
+                .await?
+            {
+                Some(item) => item,
+                // Decided by a concurrent request
+                None => continue,
+            };
+
+            if item.decision == AccessReviewDecision::Revoked {
+                self.revoke_item(&item, false, &request.reviewer).await?;
+            }
+            decided.push(item);
+        }
+
+        self.audit_service.log_compliance_event(
+            tenant_id,
+            "access_review",
+            "attest_access",
+            AuditOutcome::Success,
+            json!({
+                "campaign_id": campaign_id,
+                "reviewer": request.reviewer,
+                "approved": decided.iter().filter(|item| item.decision == AccessReviewDecision::Approved).count(),
+                "revoked": decided.iter().filter(|item| item.decision == AccessReviewDecision::Revoked).count(),
+            }),
+        ).await?;
+
+        // Reload so revocation outcomes are included
+        let ids: Vec<Uuid> = decided.iter().map(|item| item.id).collect();
+        Ok(self
+            .repository
+            .list_items(campaign_id, None)
+            .await?
+            .into_iter()
+            .filter(|item| ids.contains(&item.id))
+            .collect())
+    }
+
+    /// Close a campaign before its deadline
+    pub async fn close_campaign(
+        &self,
+        tenant_id: &str,
+        campaign_id: Uuid,
+        closed_by: &str,
+    ) -> SecurityResult<AccessReviewSummary> {
+        let campaign = self.open_campaign(tenant_id, campaign_id).await?;
+        self.close(campaign, closed_by).await
+    }
+
+    /// Start the campaigns schedules ask for and close those past their deadline
+    pub async fn run_due(&self) -> SecurityResult<AccessReviewMaintenanceSummary> {
+        let mut summary = AccessReviewMaintenanceSummary::default();
+        let now = Utc::now();
+
+        for campaign in self.repository.list_expired_campaigns(now).await? {
+            let campaign_id = campaign.id;
+            match self.close(campaign, SCHEDULER).await {
+                Ok(_) => summary.completed += 1,
+                Err(e) => {
+                    error!(campaign_id = %campaign_id, error = %e, "Failed to close access review");
+                    summary.failed += 1;
+                }
+            }
+        }
+
+        for schedule in self.repository.list_due_schedules(now).await? {
+            let request = StartAccessReviewRequest {
+                name: None,
+                review_days: Some(schedule.review_days),
+                auto_revoke: Some(schedule.auto_revoke),
+                created_by: SCHEDULER.to_string(),
+            };
+            match self.start_campaign(&schedule.tenant_id, request).await {
+                Ok(_) => {
+                    let next_campaign_at = now + Duration::days(schedule.interval_days as i64);
+                    self.repository.mark_schedule_started(&schedule.tenant_id, now, next_campaign_at).await?;
+                    summary.started += 1;
+                }
+                // The previous campaign is still running; try again next pass
+                Err(SecurityError::Conflict(_)) => {}
+                Err(e) => {
+                    error!(tenant_id = %schedule.tenant_id, error = %e, "Failed to start scheduled access review");
+                    summary.failed += 1;
+                }
+            }
+        }
+
+        Ok(summary)
+    }
+
+    pub async fn get_campaign(&self, tenant_id: &str, campaign_id: Uuid) -> SecurityResult<AccessReviewSummary> {
+        let campaign = self.find_campaign(tenant_id, campaign_id).await?;
+        self.summary(campaign).await
+    }
+
+    pub async fn list_campaigns(&self, tenant_id: &str) -> SecurityResult<Vec<AccessReviewCampaign>> {
+        self.repository.list_campaigns(tenant_id, MAX_CAMPAIGNS_LISTED).await
+    }
+
+    pub async fn list_items(
+        &self,
+        tenant_id: &str,
+        campaign_id: Uuid,
+        decision: Option<AccessReviewDecision>,
+    ) -> SecurityResult<Vec<AccessReviewItem>> {
+        self.find_campaign(tenant_id, campaign_id).await?;
+        self.repository.list_items(campaign_id, decision).await
+    }
+
+    /// The archived evidence of a completed campaign and its hash
+    pub async fn evidence(&self, tenant_id: &str, campaign_id: Uuid) -> SecurityResult<Value> {
+        let campaign = self.find_campaign(tenant_id, campaign_id).await?;
+        match (campaign.evidence, campaign.evidence_hash) {
+            (Some(evidence), Some(evidence_hash)) => Ok(json!({
+                "campaign_id": campaign.id,
+                "evidence": evidence,
+                "evidence_hash": evidence_hash,
+            })),
+            _ => Err(SecurityError::Conflict(format!("Access review {} has not completed", campaign_id))),
+        }
+    }
+
+    /// Completed campaigns' evidence within a period, for compliance reports
+    pub async fn completed_campaigns(
+        &self,
+        tenant_id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> SecurityResult<Vec<AccessReviewCampaign>> {
+        self.repository.list_completed_campaigns(tenant_id, start, end).await
+    }
+
+    pub async fn get_schedule(&self, tenant_id: &str) -> SecurityResult<Option<AccessReviewSchedule>> {
+        self.repository.get_schedule(tenant_id).await
+    }
+
+    /// Run a campaign every `interval_days`. A tenant that has never been
+    /// reviewed gets its first campaign on the next pass.
+    pub async fn set_schedule(
+        &self,
+        tenant_id: &str,
+        request: SetAccessReviewScheduleRequest,
+    ) -> SecurityResult<AccessReviewSchedule> {
+        if !(1..=MAX_INTERVAL_DAYS).contains(&request.interval_days) {
+            return Err(SecurityError::Validation(format!(
+                "interval_days must be between 1 and {}",
+                MAX_INTERVAL_DAYS
+            )));
+        }
+        let review_days = request.review_days.unwrap_or(self.config.default_review_days);
+        validate_review_days(review_days)?;
+        if review_days > request.interval_days {
+            return Err(SecurityError::Validation("review_days cannot exceed interval_days".to_string()));
+        }
+
+        let now = Utc::now();
+        let existing = self.repository.get_schedule(tenant_id).await?;
+        let last_campaign_at = existing.as_ref().and_then(|schedule| schedule.last_campaign_at);
+        let next_campaign_at = match last_campaign_at {
+            Some(last_campaign_at) => last_campaign_at + Duration::days(request.interval_days as i64),
+            None => now,
+        };
+
+        let schedule = self
+            .repository
+            .upsert_schedule(AccessReviewSchedule {
+                tenant_id: tenant_id.to_string(),
+                interval_days: request.interval_days,
+                review_days,
+                auto_revoke: request.auto_revoke.unwrap_or(true),
+                enabled: request.enabled.unwrap_or(true),
+                last_campaign_at,
+                next_campaign_at,
+                created_at: existing.as_ref().map_or(now, |schedule| schedule.created_at),
+                updated_at: now,
+            })
+            .await?;
+
+        self.audit_service.log_compliance_event(
+            tenant_id,
+            "access_review",
+            "update_access_review_schedule",
+            AuditOutcome::Success,
+            json!({
+                "interval_days": schedule.interval_days,
+                "review_days": schedule.review_days,
+                "auto_revoke": schedule.auto_revoke,
+                "enabled": schedule.enabled,
+            }),
+        ).await?;
+
+        Ok(schedule)
+    }
+
+    async fn find_campaign(&self, tenant_id: &str, campaign_id: Uuid) -> SecurityResult<AccessReviewCampaign> {
+        self.repository
+            .get_campaign(tenant_id, campaign_id)
+            .await?
+            .ok_or_else(|| SecurityError::NotFound(format!("Access review {} not found", campaign_id)))
+    }
+
+    async fn open_campaign(&self, tenant_id: &str, campaign_id: Uuid) -> SecurityResult<AccessReviewCampaign> {
+        let campaign = self.find_campaign(tenant_id, campaign_id).await?;
+        if campaign.status != AccessReviewStatus::Open {
+            return Err(SecurityError::Conflict(format!("Access review {} is not open", campaign_id)));
+        }
+        Ok(campaign)
+    }
+
+    async fn summary(&self, campaign: AccessReviewCampaign) -> SecurityResult<AccessReviewSummary> {
+        let (total_items, pending, approved, revoked, auto_revoked, revocation_failures) =
+            self.repository.item_counts(campaign.id).await?;
+
+        Ok(AccessReviewSummary {
+            campaign,
+            total_items,
+            pending,
+            approved,
+            revoked,
+            auto_revoked,
+            revocation_failures,
+        })
+    }
+
+    /// Revoke what nobody attested to (when the campaign says so), then
+    /// archive the outcome with a hash of the archived evidence
+    async fn close(&self, campaign: AccessReviewCampaign, closed_by: &str) -> SecurityResult<AccessReviewSummary> {
+        if campaign.auto_revoke {
+            for item in self.repository.list_items(campaign.id, Some(AccessReviewDecision::Pending)).await? {
+                self.revoke_item(&item, true, closed_by).await?;
+            }
+        }
+
+        let items = self.repository.list_items(campaign.id, None).await?;
+        let (total_items, pending, approved, revoked, auto_revoked, revocation_failures) =
+            self.repository.item_counts(campaign.id).await?;
+        let evidence = json!({
+            "campaign": {
+                "id": campaign.id,
+                "tenant_id": campaign.tenant_id,
+                "name": campaign.name,
+                "created_by": campaign.created_by,
+                "created_at": campaign.created_at,
+                "deadline": campaign.deadline,
+                "auto_revoke": campaign.auto_revoke,
+            },
+            "closed_by": closed_by,
+            "closed_at": Utc::now(),
+            "summary": {
+                "total_items": total_items,
+                "approved": approved,
+                "revoked": revoked,
+                "auto_revoked": auto_revoked,
+                "unattested": pending,
+                "revocation_failures": revocation_failures,
+            },
+            "items": items,
+        });
+        let evidence_hash = hex::encode(digest::digest(&digest::SHA256, &serde_json::to_vec(&evidence)?).as_ref());
+
+        let completed = self.repository.complete_campaign(campaign.id, &evidence, &evidence_hash).await?;
+
+        info!(
+            tenant_id = %completed.tenant_id,
+            campaign_id = %completed.id,
+            approved = approved,
+            revoked = revoked,
+            auto_revoked = auto_revoked,
+            "Completed access review"
+        );
+        self.audit_service.log_compliance_event(
+            &completed.tenant_id,
+            "access_review",
+            "complete_access_review",
+            if revocation_failures == 0 { AuditOutcome::Success } else { AuditOutcome::Warning },
+            json!({
+                "campaign_id": completed.id,
+                "closed_by": closed_by,
+                "summary": evidence["summary"],
+                "evidence_hash": evidence_hash,
+            }),
+        ).await?;
+
+        self.summary(completed).await
+    }
+
+    /// Remove an item's access. A failure is recorded on the item rather than
+    /// failing the review, so one unreachable service does not hold up the rest.
+    async fn revoke_item(&self, item: &AccessReviewItem, auto_revoked: bool, actor: &str) -> SecurityResult<()> {
+        let result = match item.subject_type {
+            AccessReviewSubject::UserRole => self.revoke_user_role(item, actor).await,
+            AccessReviewSubject::ModuleGrant => self.revoke_module_grant(item, actor).await,
+        };
+
+        let error = result.err().map(|e| {
+            warn!(item_id = %item.id, subject = %item.subject_name, error = %e, "Failed to revoke reviewed access");
+            e.to_string()
+        });
+        self.repository.record_revocation(item.id, auto_revoked, actor, error.as_deref()).await
+    }
+
+    async fn collect_items(&self, campaign: &AccessReviewCampaign) -> SecurityResult<Vec<AccessReviewItem>> {
+        let item = |subject_type, subject_id: String, subject_name: String, entitlement: String, details| AccessReviewItem {
+            id: Uuid::new_v4(),
+            campaign_id: campaign.id,
+            tenant_id: campaign.tenant_id.clone(),
+            subject_type,
+            subject_id,
+            subject_name,
+            entitlement,
+            details,
+            decision: AccessReviewDecision::Pending,
+            decided_by: None,
+            decided_at: None,
+            comment: None,
+            revoked_at: None,
+            auto_revoked: false,
+            revocation_error: None,
+        };
+
+        let mut items = Vec::new();
+        for user in self.list_users(&campaign.tenant_id, &campaign.created_by).await? {
+            for role in &user.roles {
+                items.push(item(
+                    AccessReviewSubject::UserRole,
+                    user.id.clone(),
+                    user.email.clone(),
+                    role.clone(),
+                    json!({ "user_status": user.status }),
+                ));
+            }
+        }
+
+        // Only active modules exercise their grants
+        for module in self.list_modules(&campaign.tenant_id).await? {
+            if module.status != json!("Active") {
+                continue;
+            }
+            if let Some(grant) = self.get_grant(&campaign.tenant_id, &module.module_id).await? {
+                items.push(item(
+                    AccessReviewSubject::ModuleGrant,
+                    module.id.clone(),
+                    module.module_id.clone(),
+                    format!("{}@{}", module.module_id, grant.version),
+                    json!({
+                        "installed_version": module.version,
+                        "capabilities": grant.capabilities,
+                        "granted_by": grant.granted_by,
+                        "granted_at": grant.granted_at,
+                    }),
+                ));
+            }
+        }
+
+        Ok(items)
+    }
+
+    async fn list_users(&self, tenant_id: &str, actor: &str) -> SecurityResult<Vec<DirectoryUser>> {
+        let mut users = Vec::new();
+        loop {
+            let url = format!(
+                "{}/api/v1/users?limit={}&offset={}",
+                self.config.user_service_url.trim_end_matches('/'),
+                USER_PAGE_SIZE,
+                users.len()
+            );
+            let request = self.http_client.get(&url).header("X-Tenant-ID", tenant_id).header("X-User-ID", actor);
+            let page: Vec<DirectoryUser> = fetch(request, "User service").await?.unwrap_or_default();
+            let last_page = page.len() < USER_PAGE_SIZE;
+            users.extend(page);
+            if last_page {
+                return Ok(users);
+            }
+        }
+    }
+
+    async fn list_modules(&self, tenant_id: &str) -> SecurityResult<Vec<ModuleInstance>> {
+        let url = format!(
+            "{}/api/v1/tenants/{}/modules",
+            self.config.module_service_url.trim_end_matches('/'),
+            tenant_id
+        );
+        let request = self.http_client.get(&url).header("X-Tenant-ID", tenant_id);
+        Ok(fetch(request, "Module service").await?.unwrap_or_default())
+    }
+
+    async fn get_grant(&self, tenant_id: &str, module_id: &str) -> SecurityResult<Option<CapabilityGrant>> {
+        let url = format!(
+            "{}/api/v1/tenants/{}/modules/{}/grant",
+            self.config.module_service_url.trim_end_matches('/'),
+            tenant_id,
+            module_id
+        );
+        let request = self.http_client.get(&url).header("X-Tenant-ID", tenant_id);
+        fetch(request, "Module service").await
+    }
+
+    /// Remove the role from the user, keeping their other roles
+    async fn revoke_user_role(&self, item: &AccessReviewItem, actor: &str) -> SecurityResult<()> {
+        let url = format!(
+            "{}/api/v1/users/{}",
+            self.config.user_service_url.trim_end_matches('/'),
+            item.subject_id
+        );
+
+        let request = self.http_client.get(&url).header("X-Tenant-ID", &item.tenant_id).header("X-User-ID", actor);
+        let user: DirectoryUser = fetch(request, "User service")
+            .await?
+            .ok_or_else(|| SecurityError::NotFound(format!("User {} not found", item.subject_id)))?;
+        if !user.roles.contains(&item.entitlement) {
+            return Ok(());
+        }
+
+        let roles: Vec<String> = user.roles.into_iter().filter(|role| role != &item.entitlement).collect();
+        let request = self
+            .http_client
+            .put(&url)
+            .header("X-Tenant-ID", &item.tenant_id)
+            .header("X-User-ID", actor)
+            .json(&json!({ "roles": roles }));
+        fetch::<Value>(request, "User service").await?;
+        Ok(())
+    }
+
+    /// Deactivate the module instance holding the grant
+    async fn revoke_module_grant(&self, item: &AccessReviewItem, actor: &str) -> SecurityResult<()> {
+        let url = format!(
+            "{}/api/v1/modules/{}/deactivate",
+            self.config.module_service_url.trim_end_matches('/'),
+            item.subject_id
+        );
+        let request = self.http_client.post(&url).header("X-Tenant-ID", &item.tenant_id).header("X-User-ID", actor);
+        fetch::<Value>(request, "Module service").await?;
+        Ok(())
+    }
+}
+
+fn validate_review_days(review_days: i32) -> SecurityResult<()> {
+    if !(1..=MAX_REVIEW_DAYS).contains(&review_days) {
+        return Err(SecurityError::Validation(format!("review_days must be between 1 and {}", MAX_REVIEW_DAYS)));
+    }
+    Ok(())
+}
+
+async fn fetch<T: DeserializeOwned>(request: reqwest::RequestBuilder, service: &str) -> SecurityResult<Option<T>> {
+    let response = request.send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(SecurityError::ServiceUnavailable(format!("{} returned {}", service, status)));
+    }
+
+    let body: ServiceResponse<T> = response.json().await?;
+    if !body.success {
+        return Err(SecurityError::ServiceUnavailable(format!(
+            "{} failed: {}",
+            service,
+            body.error.unwrap_or_else(|| "unknown error".to_string())
+        )));
+    }
+    Ok(body.data)
+}
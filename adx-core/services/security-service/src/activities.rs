@@ -1,15 +1,22 @@
 use crate::{
     error::{SecurityError, SecurityResult},
     models::{
-        GdprExportRequest, GdprDeletionRequest, SecurityScanRequest, AuditOutcome,
-        DeletionMethod
+        GdprExportRequest, GdprDeletionRequest, SecurityScanRequest, AuditOutcome, AuditLogFilter,
+        DeletionMethod, DataDestructionCertificate, IssueDestructionCertificateRequest,
+        ComplianceAssessment, ComplianceReport, ComplianceReportType, EvidenceItem,
+        GenerateComplianceReportRequest, KeyPurpose, AccessReviewStatus, AccessReviewSummary,
+        StartAccessReviewRequest, ContainmentAction, RequestContainmentRequest,
     },
     workflows::{ScanAnalysis, ComplianceAnalysis, ThreatAnalysis, SecurityResponseAction},
     audit::AuditService,
     gdpr::GdprService,
     retention::DataRetentionService,
     scanning::SecurityScanningService,
-    compliance::ComplianceService,
+    compliance::{self, ComplianceService},
+    destruction::DestructionCertificateService,
+    keys::KeyManagementService,
+    access_reviews::AccessReviewService,
+    incidents::IncidentService,
 };
 use chrono::{DateTime, Utc};
 use serde_json::Value;
@@ -25,6 +32,10 @@ pub struct SecurityActivities {
     retention_service: Arc<DataRetentionService>,
     scanning_service: Arc<SecurityScanningService>,
     compliance_service: Arc<ComplianceService>,
+    destruction_service: Arc<DestructionCertificateService>,
+    key_service: Arc<KeyManagementService>,
+    access_review_service: Arc<AccessReviewService>,
+    incident_service: Arc<IncidentService>,
 }
 
 impl SecurityActivities {
@@ -34,6 +45,10 @@ impl SecurityActivities {
         retention_service: Arc<DataRetentionService>,
         scanning_service: Arc<SecurityScanningService>,
         compliance_service: Arc<ComplianceService>,
+        destruction_service: Arc<DestructionCertificateService>,
+        key_service: Arc<KeyManagementService>,
+        access_review_service: Arc<AccessReviewService>,
+        incident_service: Arc<IncidentService>,
     ) -> Self {
         Self {
             audit_service,
@@ -41,6 +56,10 @@ impl SecurityActivities {
             retention_service,
             scanning_service,
             compliance_service,
+            destruction_service,
+            key_service,
+            access_review_service,
+            incident_service,
         }
     }
 
@@ -395,16 +414,12 @@ impl SecurityActivities {
         );
 
         // This would collect audit logs for the period
-        let audit_response = self.audit_service.get_audit_logs(
-            &tenant_id,
-            Some(period_start),
-            Some(period_end),
-            None,
-            None,
-            None,
-            1,
-            1000,
-        ).await?;
+        let filter = AuditLogFilter {
+            start_date: Some(period_start),
+            end_date: Some(period_end),
+            ..Default::default()
+        };
+        let audit_response = self.audit_service.get_audit_logs(&tenant_id, &filter, 1, 1000).await?;
 
         Ok(serde_json::to_value(audit_response)?)
     }
@@ -488,6 +503,182 @@ impl SecurityActivities {
         Ok(Uuid::new_v4())
     }
 
+    #[activity]
+    pub async fn gather_compliance_evidence(
+        &self,
+        tenant_id: Option<String>,
+        report_type: ComplianceReportType,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> SecurityResult<Vec<EvidenceItem>> {
+        info!(
+            tenant_id = ?tenant_id,
+            report_type = ?report_type,
+            "Gathering compliance evidence"
+        );
+
+        let template = compliance::template(&report_type)?;
+        self.compliance_service
+            .gather_evidence(tenant_id.as_deref(), &template, period_start, period_end)
+            .await
+    }
+
+    #[activity]
+    pub async fn assess_compliance_evidence(
+        &self,
+        report_type: ComplianceReportType,
+        evidence: Vec<EvidenceItem>,
+    ) -> SecurityResult<ComplianceAssessment> {
+        let template = compliance::template(&report_type)?;
+        Ok(compliance::assess(&template, &evidence))
+    }
+
+    #[activity]
+    pub async fn store_compliance_report(
+        &self,
+        tenant_id: Option<String>,
+        request: GenerateComplianceReportRequest,
+        assessment: ComplianceAssessment,
+        evidence: Vec<EvidenceItem>,
+    ) -> SecurityResult<ComplianceReport> {
+        let template = compliance::template(&request.report_type)?;
+        self.compliance_service
+            .store_report(tenant_id.as_deref(), &template, &request, assessment, evidence)
+            .await
+    }
+
+    #[activity]
+    pub async fn issue_destruction_certificate(
+        &self,
+        request: IssueDestructionCertificateRequest,
+    ) -> SecurityResult<DataDestructionCertificate> {
+        info!(
+            tenant_id = %request.tenant_id,
+            certificate_id = %request.certificate_id,
+            services = %request.purged_services.len(),
+            "Issuing data destruction certificate"
+        );
+
+        self.destruction_service.issue_certificate(request).await
+    }
+
+    // Key Rotation Activities
+
+    /// Stage a new key and return its id; a key already pending is reused
+    #[activity]
+    pub async fn stage_rotation_key(
+        &self,
+        purpose: KeyPurpose,
+        owner_id: String,
+        requested_by: String,
+    ) -> SecurityResult<String> {
+        info!(purpose = purpose.as_str(), owner_id = %owner_id, "Staging rotation key");
+
+        let key = self.key_service.stage_key(purpose, &owner_id, &requested_by).await?;
+        Ok(key.kid)
+    }
+
+    /// Activate the staged key; returns when the key it replaced stops being accepted
+    #[activity]
+    pub async fn activate_rotation_key(&self, kid: String, overlap_hours: Option<i32>) -> SecurityResult<DateTime<Utc>> {
+        info!(kid = %kid, "Activating rotation key");
+
+        self.key_service.activate(&kid, overlap_hours).await
+    }
+
+    #[activity]
+    pub async fn retire_expired_keys(&self, purpose: KeyPurpose, owner_id: String) -> SecurityResult<usize> {
+        info!(purpose = purpose.as_str(), owner_id = %owner_id, "Retiring expired keys");
+
+        self.key_service.retire_expired(purpose, &owner_id).await
+    }
+
+    // Access Review Activities
+
+    #[activity]
+    pub async fn start_access_review(
+        &self,
+        tenant_id: String,
+        request: StartAccessReviewRequest,
+    ) -> SecurityResult<AccessReviewSummary> {
+        info!(tenant_id = %tenant_id, "Starting access review");
+
+        self.access_review_service.start_campaign(&tenant_id, request).await
+    }
+
+    /// Close the campaign; one the scheduler already closed is returned as it is
+    #[activity]
+    pub async fn close_access_review(
+        &self,
+        tenant_id: String,
+        campaign_id: Uuid,
+        closed_by: String,
+    ) -> SecurityResult<AccessReviewSummary> {
+        info!(tenant_id = %tenant_id, campaign_id = %campaign_id, "Closing access review");
+
+        let summary = self.access_review_service.get_campaign(&tenant_id, campaign_id).await?;
+        if summary.campaign.status != AccessReviewStatus::Open {
+            return Ok(summary);
+        }
+        self.access_review_service.close_campaign(&tenant_id, campaign_id, &closed_by).await
+    }
+
+    // Incident Containment Activities
+
+    /// Actions that need no approval have already run when this returns
+    #[activity]
+    pub async fn request_containment_action(
+        &self,
+        tenant_id: String,
+        incident_id: Uuid,
+        request: RequestContainmentRequest,
+    ) -> SecurityResult<ContainmentAction> {
+        info!(
+            tenant_id = %tenant_id,
+            incident_id = %incident_id,
+            action = request.action_type.as_str(),
+            target = %request.target,
+            "Requesting containment action"
+        );
+
+        self.incident_service.request_action(&tenant_id, incident_id, request).await
+    }
+
+    #[activity]
+    pub async fn get_containment_action(
+        &self,
+        tenant_id: String,
+        incident_id: Uuid,
+        action_id: Uuid,
+    ) -> SecurityResult<ContainmentAction> {
+        self.incident_service.get_action(&tenant_id, incident_id, action_id).await
+    }
+
+    /// Run an approved action; one that already ran is returned as it is
+    #[activity]
+    pub async fn execute_containment_action(
+        &self,
+        tenant_id: String,
+        incident_id: Uuid,
+        action_id: Uuid,
+    ) -> SecurityResult<ContainmentAction> {
+        info!(tenant_id = %tenant_id, incident_id = %incident_id, action_id = %action_id, "Executing containment action");
+
+        self.incident_service.execute_action(&tenant_id, incident_id, action_id).await
+    }
+
+    #[activity]
+    pub async fn expire_containment_action(
+        &self,
+        tenant_id: String,
+        incident_id: Uuid,
+        action_id: Uuid,
+    ) -> SecurityResult<ContainmentAction> {
+        warn!(tenant_id = %tenant_id, incident_id = %incident_id, action_id = %action_id, "Containment action was not approved in time");
+
+        self.incident_service.expire_action(&tenant_id, incident_id, action_id).await
+    }
+
     // Security Response Activities
 
     #[activity]
@@ -1,16 +1,84 @@
 use crate::{
     error::{SecurityError, SecurityResult},
-    models::{AuditLog, AuditEventCategory, AuditOutcome, CreateAuditLogRequest, AuditLogResponse},
+    models::{
+        AuditLog, AuditEventCategory, AuditOutcome, CreateAuditLogRequest, AuditLogResponse,
+        AuditLogFilter, AuditChainVerification, AuditRetentionPolicy, AuditRetentionSettings,
+        IngestAuditEventsResponse, SetAuditRetentionPolicyRequest,
+    },
     repositories::AuditRepository,
     encryption::EncryptionService,
 };
-use chrono::{DateTime, Utc};
+use adx_shared::audit::AuditEvent;
+use chrono::{DateTime, SubsecRound, Utc};
+use ring::digest;
+use serde::Serialize;
 use serde_json::Value;
 use std::sync::Arc;
 use tokio::sync::RwLock;
 use tracing::{info, warn, error};
 use uuid::Uuid;
 
+/// Name recorded on events security-service logs itself
+pub const SERVICE_NAME: &str = "security-service";
+
+/// What the first event of a tenant's chain links back to
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Audit events read per query while a chain is verified
+const VERIFY_PAGE_SIZE: i64 = 1000;
+
+/// Fields covered by an event's chain hash, in a fixed order so the hash can
+/// be recomputed from the stored event.
+#[derive(Serialize)]
+struct ChainedEvent<'a> {
+    sequence: Option<i64>,
+    previous_hash: Option<&'a str>,
+    id: Uuid,
+    tenant_id: &'a str,
+    service: &'a str,
+    user_id: Option<&'a str>,
+    session_id: Option<&'a str>,
+    event_type: &'a str,
+    event_category: &'a AuditEventCategory,
+    resource_type: &'a str,
+    resource_id: Option<&'a str>,
+    action: &'a str,
+    outcome: &'a AuditOutcome,
+    ip_address: Option<&'a str>,
+    user_agent: Option<&'a str>,
+    request_id: Option<&'a str>,
+    details: &'a Value,
+    risk_score: Option<i32>,
+    created_at: DateTime<Utc>,
+}
+
+/// Hex SHA-256 over the event, its sequence and the hash it links back to
+pub fn chain_hash(log: &AuditLog) -> SecurityResult<String> {
+    let body = serde_json::to_vec(&ChainedEvent {
+        sequence: log.sequence,
+        previous_hash: log.previous_hash.as_deref(),
+        id: log.id,
+        tenant_id: &log.tenant_id,
+        service: &log.service,
+        user_id: log.user_id.as_deref(),
+        session_id: log.session_id.as_deref(),
+        event_type: &log.event_type,
+        event_category: &log.event_category,
+        resource_type: &log.resource_type,
+        resource_id: log.resource_id.as_deref(),
+        action: &log.action,
+        outcome: &log.outcome,
+        ip_address: log.ip_address.as_deref(),
+        user_agent: log.user_agent.as_deref(),
+        request_id: log.request_id.as_deref(),
+        details: &log.details,
+        risk_score: log.risk_score,
+        created_at: log.created_at,
+    })?;
+
+    Ok(hex::encode(digest::digest(&digest::SHA256, &body).as_ref()))
+}
+
 #[derive(Clone)]
 pub struct AuditService {
     repository: Arc<AuditRepository>,
@@ -55,6 +123,28 @@ impl AuditService {
         Ok(audit_log.id)
     }
 
+    /// Queue events delivered by other services. Events are checked as a
+    /// whole, so a malformed batch is rejected without any of it being logged.
+    pub async fn ingest_events(&self, events: Vec<AuditEvent>) -> SecurityResult<IngestAuditEventsResponse> {
+        for event in &events {
+            if event.tenant_id.trim().is_empty() || event.service.trim().is_empty() || event.action.trim().is_empty() {
+                return Err(SecurityError::Validation(format!(
+                    "Audit event {} needs a tenant, service and action", event.id
+                )));
+            }
+        }
+
+        let mut event_ids = Vec::with_capacity(events.len());
+        for event in events {
+            event_ids.push(self.log_event(CreateAuditLogRequest::from(event)).await?);
+        }
+
+        Ok(IngestAuditEventsResponse {
+            accepted: event_ids.len(),
+            event_ids,
+        })
+    }
+
     /// Log authentication event
     pub async fn log_authentication(
         &self,
@@ -67,7 +157,9 @@ impl AuditService {
         details: Value,
     ) -> SecurityResult<Uuid> {
         self.log_event(CreateAuditLogRequest {
+            event_id: None,
             tenant_id: tenant_id.to_string(),
+            service: SERVICE_NAME.to_string(),
             user_id: user_id.map(|s| s.to_string()),
             session_id: None,
             event_type: "authentication".to_string(),
@@ -80,6 +172,7 @@ impl AuditService {
             user_agent: user_agent.map(|s| s.to_string()),
             request_id: None,
             details,
+            occurred_at: None,
         }).await
     }
 
@@ -95,7 +188,9 @@ impl AuditService {
         details: Value,
     ) -> SecurityResult<Uuid> {
         self.log_event(CreateAuditLogRequest {
+            event_id: None,
             tenant_id: tenant_id.to_string(),
+            service: SERVICE_NAME.to_string(),
             user_id: Some(user_id.to_string()),
             session_id: None,
             event_type: "data_access".to_string(),
@@ -108,6 +203,7 @@ impl AuditService {
             user_agent: None,
             request_id: None,
             details,
+            occurred_at: None,
         }).await
     }
 
@@ -132,7 +228,9 @@ impl AuditService {
         }
 
         self.log_event(CreateAuditLogRequest {
+            event_id: None,
             tenant_id: tenant_id.to_string(),
+            service: SERVICE_NAME.to_string(),
             user_id: Some(user_id.to_string()),
             session_id: None,
             event_type: "data_modification".to_string(),
@@ -145,6 +243,7 @@ impl AuditService {
             user_agent: None,
             request_id: None,
             details: Value::Object(details),
+            occurred_at: None,
         }).await
     }
 
@@ -163,7 +262,9 @@ impl AuditService {
         event_details.insert("additional_details".to_string(), details);
 
         self.log_event(CreateAuditLogRequest {
+            event_id: None,
             tenant_id: tenant_id.to_string(),
+            service: SERVICE_NAME.to_string(),
             user_id: None,
             session_id: None,
             event_type: event_type.to_string(),
@@ -176,6 +277,7 @@ impl AuditService {
             user_agent: None,
             request_id: None,
             details: Value::Object(event_details),
+            occurred_at: None,
         }).await
     }
 
@@ -193,7 +295,9 @@ impl AuditService {
         event_details.insert("additional_details".to_string(), details);
 
         self.log_event(CreateAuditLogRequest {
+            event_id: None,
             tenant_id: tenant_id.to_string(),
+            service: SERVICE_NAME.to_string(),
             user_id: None,
             session_id: None,
             event_type: "compliance".to_string(),
@@ -206,6 +310,7 @@ impl AuditService {
             user_agent: None,
             request_id: None,
             details: Value::Object(event_details),
+            occurred_at: None,
         }).await
     }
 
@@ -213,33 +318,12 @@ impl AuditService {
     pub async fn get_audit_logs(
         &self,
         tenant_id: &str,
-        start_date: Option<DateTime<Utc>>,
-        end_date: Option<DateTime<Utc>>,
-        event_category: Option<AuditEventCategory>,
-        user_id: Option<&str>,
-        resource_type: Option<&str>,
+        filter: &AuditLogFilter,
         page: i32,
         page_size: i32,
     ) -> SecurityResult<AuditLogResponse> {
-        let logs = self.repository.get_audit_logs(
-            tenant_id,
-            start_date,
-            end_date,
-            event_category,
-            user_id,
-            resource_type,
-            page,
-            page_size,
-        ).await?;
-
-        let total_count = self.repository.count_audit_logs(
-            tenant_id,
-            start_date,
-            end_date,
-            event_category,
-            user_id,
-            resource_type,
-        ).await?;
+        let logs = self.repository.get_audit_logs(tenant_id, filter, page, page_size).await?;
+        let total_count = self.repository.count_audit_logs(tenant_id, filter).await?;
 
         // Decrypt sensitive details if encryption is enabled
         let decrypted_logs = if self.encryption_enabled {
@@ -297,6 +381,123 @@ impl AuditService {
         Ok(())
     }
 
+    /// Recompute a tenant's hash chain from where retention last cut it.
+    /// Any edited, removed or reordered event breaks the chain at that point.
+    pub async fn verify_chain(&self, tenant_id: &str) -> SecurityResult<AuditChainVerification> {
+        let anchor = self.repository.latest_retention_run(tenant_id).await?
+            .and_then(|run| run.last_deleted_sequence.zip(run.last_deleted_hash));
+        let (mut expected_sequence, mut expected_hash) = match anchor {
+            Some((sequence, hash)) => (sequence + 1, hash),
+            None => (1, GENESIS_HASH.to_string()),
+        };
+        let head = self.repository.get_chain_head(tenant_id).await?;
+
+        let mut verification = AuditChainVerification {
+            tenant_id: tenant_id.to_string(),
+            valid: true,
+            verified_events: 0,
+            first_sequence: None,
+            last_sequence: None,
+            broken_at_sequence: None,
+            reason: None,
+            verified_at: Utc::now(),
+        };
+
+        loop {
+            let segment = self.repository
+                .get_chain_segment(tenant_id, expected_sequence - 1, VERIFY_PAGE_SIZE)
+                .await?;
+            if segment.is_empty() {
+                break;
+            }
+
+            for log in &segment {
+                let sequence = log.sequence.unwrap_or_default();
+                let failure = if sequence != expected_sequence {
+                    Some(format!("Expected event {} but found event {}", expected_sequence, sequence))
+                } else if log.previous_hash.as_deref() != Some(expected_hash.as_str()) {
+                    Some("Event does not link to the event before it".to_string())
+                } else if log.hash.as_deref() != Some(chain_hash(log)?.as_str()) {
+                    Some("Event hash does not match its contents".to_string())
+                } else {
+                    None
+                };
+
+                if let Some(reason) = failure {
+                    warn!(tenant_id = %tenant_id, sequence = expected_sequence, reason = %reason, "Audit chain broken");
+                    verification.valid = false;
+                    verification.broken_at_sequence = Some(expected_sequence);
+                    verification.reason = Some(reason);
+                    return Ok(verification);
+                }
+
+                verification.first_sequence.get_or_insert(sequence);
+                verification.last_sequence = Some(sequence);
+                verification.verified_events += 1;
+                expected_sequence += 1;
+                expected_hash = log.hash.clone().unwrap_or_default();
+            }
+        }
+
+        // Events cut from the end of the chain leave the head pointing past them
+        if let Some((head_sequence, head_hash)) = head {
+            if head_sequence != expected_sequence - 1 || (head_sequence > 0 && head_hash != expected_hash) {
+                verification.valid = false;
+                verification.broken_at_sequence = Some(expected_sequence);
+                verification.reason = Some(format!("Chain ends at event {} but its head is event {}", expected_sequence - 1, head_sequence));
+            }
+        }
+
+        Ok(verification)
+    }
+
+    pub async fn get_retention_settings(&self, tenant_id: &str, default_retention_days: i32) -> SecurityResult<AuditRetentionSettings> {
+        let policy = self.repository.get_retention_policy(tenant_id).await?;
+        let last_run = self.repository.latest_retention_run(tenant_id).await?;
+
+        Ok(AuditRetentionSettings {
+            tenant_id: tenant_id.to_string(),
+            retention_days: policy.as_ref().map_or(default_retention_days, |policy| policy.retention_days),
+            is_default: policy.is_none(),
+            last_run,
+        })
+    }
+
+    pub async fn set_retention_policy(
+        &self,
+        tenant_id: &str,
+        request: SetAuditRetentionPolicyRequest,
+    ) -> SecurityResult<AuditRetentionPolicy> {
+        if request.retention_days <= 0 {
+            return Err(SecurityError::Validation("Retention must be at least one day".to_string()));
+        }
+
+        let policy = self.repository.upsert_retention_policy(tenant_id, request.retention_days, request.updated_by.as_deref()).await?;
+        self.log_compliance_event(
+            tenant_id,
+            "audit_retention",
+            "update_retention_policy",
+            AuditOutcome::Success,
+            serde_json::json!({
+                "retention_days": policy.retention_days,
+                "updated_by": policy.updated_by,
+            }),
+        ).await?;
+
+        Ok(policy)
+    }
+
+    /// Remove every tenant's events older than its retention
+    pub async fn apply_retention_policies(&self, default_retention_days: i32) -> SecurityResult<i64> {
+        let mut deleted_count = 0;
+        for tenant_id in self.repository.list_audited_tenants().await? {
+            let retention_days = self.repository.get_retention_policy(&tenant_id).await?
+                .map_or(default_retention_days, |policy| policy.retention_days);
+            deleted_count += self.cleanup_old_logs(&tenant_id, retention_days).await?;
+        }
+        Ok(deleted_count)
+    }
+
     /// Clean up old audit logs based on retention policy
     pub async fn cleanup_old_logs(&self, tenant_id: &str, retention_days: i32) -> SecurityResult<i64> {
         let cutoff_date = Utc::now() - chrono::Duration::days(retention_days as i64);
@@ -326,8 +527,9 @@ impl AuditService {
         let risk_score = self.calculate_risk_score(&request);
 
         Ok(AuditLog {
-            id: Uuid::new_v4(),
+            id: request.event_id.unwrap_or_else(Uuid::new_v4),
             tenant_id: request.tenant_id,
+            service: request.service,
             user_id: request.user_id,
             session_id: request.session_id,
             event_type: request.event_type,
@@ -341,7 +543,12 @@ impl AuditService {
             request_id: request.request_id,
             details,
             risk_score: Some(risk_score),
-            created_at: Utc::now(),
+            // Stored to the microsecond, so the chain hash can be recomputed
+            created_at: request.occurred_at.unwrap_or_else(Utc::now).trunc_subsecs(6),
+            // Linked into the tenant's chain when the batch is stored
+            sequence: None,
+            previous_hash: None,
+            hash: None,
         })
     }
 
@@ -371,7 +578,7 @@ impl AuditService {
         }))
     }
 
-    async fn decrypt_audit_logs(&self, logs: Vec<AuditLog>) -> SecurityResult<Vec<AuditLog>> {
+    pub(crate) async fn decrypt_audit_logs(&self, logs: Vec<AuditLog>) -> SecurityResult<Vec<AuditLog>> {
         let mut decrypted_logs = Vec::new();
         
         for mut log in logs {
@@ -420,7 +627,7 @@ impl AuditService {
         let mut csv_content = String::new();
         
         // CSV header
-        csv_content.push_str("id,tenant_id,user_id,event_type,event_category,resource_type,resource_id,action,outcome,ip_address,user_agent,risk_score,created_at,details\n");
+        csv_content.push_str("id,tenant_id,service,user_id,event_type,event_category,resource_type,resource_id,action,outcome,ip_address,user_agent,risk_score,created_at,sequence,hash,details\n");
         
         // CSV rows
         for log in logs {
@@ -429,9 +636,10 @@ impl AuditService {
                 .replace('"', '""'); // Escape quotes for CSV
             
             csv_content.push_str(&format!(
-                "{},{},{},{},{:?},{},{},{},{:?},{},{},{},{},{}\n",
+                "{},{},{},{},{},{:?},{},{},{},{:?},{},{},{},{},{},{},{}\n",
                 log.id,
                 log.tenant_id,
+                log.service,
                 log.user_id.unwrap_or_default(),
                 log.event_type,
                 log.event_category,
@@ -443,6 +651,8 @@ impl AuditService {
                 log.user_agent.unwrap_or_default(),
                 log.risk_score.unwrap_or(0),
                 log.created_at.format("%Y-%m-%d %H:%M:%S UTC"),
+                log.sequence.map(|sequence| sequence.to_string()).unwrap_or_default(),
+                log.hash.unwrap_or_default(),
                 format!("\"{}\"", details_str)
             ));
         }
@@ -0,0 +1,568 @@
+use crate::{
+    audit::AuditService,
+    detection::ESCALATION_ACTIONS,
+    encryption::EncryptionService,
+    error::{SecurityError, SecurityResult},
+    models::{
+        AlertRule, AlertStatus, AuditLogFilter, AuditOutcome, ComplianceAssessment,
+        ComplianceReport, ComplianceReportType, ComplianceStatus, ComplianceTemplate,
+        ControlResult, ControlTemplate, EvidenceItem, EvidenceKind, GenerateComplianceReportRequest,
+        RiskLevel, SecurityEventSeverity,
+    },
+    repositories::{AccessReviewRepository, AuditRepository, ComplianceRepository, DetectionRepository},
+    retention::DataRetentionService,
+};
+use chrono::{DateTime, Utc};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tracing::info;
+use uuid::Uuid;
+
+/// Tenant id platform-wide reports are stored under
+pub const PLATFORM_SCOPE_ID: &str = "platform";
+
+/// Audit events included in a report's sample
+const AUDIT_SAMPLE_SIZE: i64 = 25;
+
+/// Longest key rotation period that counts as managed key rotation
+const MAX_KEY_ROTATION_DAYS: u32 = 365;
+
+/// Reports listed per request
+const REPORT_LIST_LIMIT: i64 = 100;
+
+fn control(id: &str, title: &str, description: &str, evidence: &[EvidenceKind]) -> ControlTemplate {
+    ControlTemplate {
+        id: id.to_string(),
+        title: title.to_string(),
+        description: description.to_string(),
+        evidence: evidence.to_vec(),
+    }
+}
+
+/// Built-in template for a framework
+pub fn template(report_type: &ComplianceReportType) -> SecurityResult<ComplianceTemplate> {
+    use EvidenceKind::*;
+
+    let (name, controls) = match report_type {
+        ComplianceReportType::Soc2 => ("SOC 2 Type II", vec![
+            control("CC6.1", "Logical access security", "Access to data is restricted to authorised users and privileged grants are reviewed", &[AccessReviews, EncryptionPosture]),
+            control("CC6.2", "Access provisioning", "Granting and widening access is recorded and triaged", &[AccessReviews, AuditSamples]),
+            control("CC6.7", "Data protection", "Data is encrypted at rest and keys are rotated", &[EncryptionPosture]),
+            control("CC7.2", "System monitoring", "Activity is logged to a tamper-evident trail and monitored for anomalies", &[AuditSamples, SecurityAlerts]),
+            control("CC7.3", "Incident evaluation", "Security alerts are triaged and resolved", &[SecurityAlerts]),
+            control("C1.2", "Disposal of confidential information", "Data is disposed of according to retention policies", &[RetentionStatus]),
+        ]),
+        ComplianceReportType::Gdpr => ("GDPR", vec![
+            control("Art. 5(1)(e)", "Storage limitation", "Personal data is kept no longer than necessary", &[RetentionStatus]),
+            control("Art. 30", "Records of processing", "Processing activities are recorded", &[AuditSamples]),
+            control("Art. 32", "Security of processing", "Personal data is encrypted and access to it is controlled", &[EncryptionPosture, AccessReviews]),
+            control("Art. 33", "Breach detection", "Potential breaches are detected and handled in time to notify", &[SecurityAlerts]),
+        ]),
+        ComplianceReportType::Hipaa => ("HIPAA Security Rule", vec![
+            control("164.308(a)(4)", "Information access management", "Access to ePHI is authorised and reviewed", &[AccessReviews]),
+            control("164.308(a)(6)", "Security incident procedures", "Security incidents are identified and responded to", &[SecurityAlerts]),
+            control("164.312(a)(2)(iv)", "Encryption and decryption", "ePHI is encrypted at rest", &[EncryptionPosture]),
+            control("164.312(b)", "Audit controls", "Activity in systems holding ePHI is recorded and examined", &[AuditSamples]),
+            control("164.312(c)(1)", "Integrity", "Records are protected from improper alteration", &[AuditSamples]),
+            control("164.316(b)(2)", "Retention", "Documentation is retained as required", &[RetentionStatus]),
+        ]),
+        other => {
+            return Err(SecurityError::Validation(format!(
+                "No compliance report template for {:?}", other
+            )));
+        }
+    };
+
+    Ok(ComplianceTemplate {
+        report_type: report_type.clone(),
+        name: name.to_string(),
+        version: "2026.1".to_string(),
+        controls,
+    })
+}
+
+/// Every built-in template
+pub fn templates() -> Vec<ComplianceTemplate> {
+    [ComplianceReportType::Soc2, ComplianceReportType::Gdpr, ComplianceReportType::Hipaa]
+        .iter()
+        .filter_map(|report_type| template(report_type).ok())
+        .collect()
+}
+
+fn recommendation(kind: EvidenceKind) -> &'static str {
+    match kind {
+        EvidenceKind::AccessReviews => {
+            "Triage open privilege escalation alerts, review privileged grants and follow up failed revocations"
+        }
+        EvidenceKind::RetentionStatus => "Define and enable data retention policies",
+        EvidenceKind::EncryptionPosture => "Enable audit log encryption and rotate keys at least yearly",
+        EvidenceKind::AuditSamples => "Investigate the broken audit chain and make sure services emit audit events",
+        EvidenceKind::SecurityAlerts => "Resolve open high and critical security alerts",
+    }
+}
+
+/// Assess a template's controls against gathered evidence. A control passes
+/// when all of its evidence was gathered and passed.
+pub fn assess(template: &ComplianceTemplate, evidence: &[EvidenceItem]) -> ComplianceAssessment {
+    let controls: Vec<ControlResult> = template.controls.iter().map(|control| {
+        let failing_evidence: Vec<EvidenceKind> = control.evidence.iter()
+            .copied()
+            .filter(|kind| !evidence.iter().any(|item| item.kind == *kind && item.passed))
+            .collect();
+
+        ControlResult {
+            id: control.id.clone(),
+            title: control.title.clone(),
+            passed: failing_evidence.is_empty(),
+            failing_evidence,
+        }
+    }).collect();
+
+    let failed = controls.iter().filter(|control| !control.passed).count();
+    let failed_ratio = if controls.is_empty() { 0.0 } else { failed as f32 / controls.len() as f32 };
+    let compliance_score = (1.0 - failed_ratio) * 100.0;
+
+    let status = if failed == 0 {
+        ComplianceStatus::Compliant
+    } else if compliance_score >= 75.0 {
+        ComplianceStatus::PartiallyCompliant
+    } else {
+        ComplianceStatus::NonCompliant
+    };
+    let risk_level = if failed == 0 {
+        RiskLevel::Low
+    } else if failed_ratio <= 0.25 {
+        RiskLevel::Medium
+    } else if failed_ratio <= 0.5 {
+        RiskLevel::High
+    } else {
+        RiskLevel::Critical
+    };
+
+    let mut recommendations: Vec<String> = Vec::new();
+    for kind in controls.iter().flat_map(|control| control.failing_evidence.iter()) {
+        let text = recommendation(*kind).to_string();
+        if !recommendations.contains(&text) {
+            recommendations.push(text);
+        }
+    }
+
+    ComplianceAssessment {
+        controls,
+        compliance_score,
+        status,
+        risk_level,
+        recommendations,
+    }
+}
+
+/// Gathers compliance evidence and produces reports for auditors, for one
+/// tenant or for the whole platform
+#[derive(Clone)]
+pub struct ComplianceService {
+    repository: Arc<ComplianceRepository>,
+    audit_repository: Arc<AuditRepository>,
+    detection_repository: Arc<DetectionRepository>,
+    access_review_repository: Arc<AccessReviewRepository>,
+    audit_service: Arc<AuditService>,
+    retention_service: Arc<DataRetentionService>,
+    encryption: Arc<EncryptionService>,
+    audit_retention_days: i32,
+    audit_encryption_enabled: bool,
+}
+
+impl ComplianceService {
+    pub fn new(
+        repository: Arc<ComplianceRepository>,
+        audit_repository: Arc<AuditRepository>,
+        detection_repository: Arc<DetectionRepository>,
+        access_review_repository: Arc<AccessReviewRepository>,
+        audit_service: Arc<AuditService>,
+        retention_service: Arc<DataRetentionService>,
+        encryption: Arc<EncryptionService>,
+        audit_retention_days: i32,
+        audit_encryption_enabled: bool,
+    ) -> Self {
+        Self {
+            repository,
+            audit_repository,
+            detection_repository,
+            access_review_repository,
+            audit_service,
+            retention_service,
+            encryption,
+            audit_retention_days,
+            audit_encryption_enabled,
+        }
+    }
+
+    /// Gather, assess and store a report. `tenant_id` of None reports on
+    /// the whole platform.
+    pub async fn generate_report(
+        &self,
+        tenant_id: Option<&str>,
+        request: GenerateComplianceReportRequest,
+    ) -> SecurityResult<ComplianceReport> {
+        if request.period_start >= request.period_end || request.period_end > Utc::now() {
+            return Err(SecurityError::Validation("Report period must end after it starts and not in the future".to_string()));
+        }
+
+        let template = template(&request.report_type)?;
+        let evidence = self.gather_evidence(tenant_id, &template, request.period_start, request.period_end).await?;
+        let assessment = assess(&template, &evidence);
+
+        self.store_report(tenant_id, &template, &request, assessment, evidence).await
+    }
+
+    /// Evidence for every kind the template's controls rely on
+    pub async fn gather_evidence(
+        &self,
+        tenant_id: Option<&str>,
+        template: &ComplianceTemplate,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> SecurityResult<Vec<EvidenceItem>> {
+        let mut kinds: Vec<EvidenceKind> = Vec::new();
+        for kind in template.controls.iter().flat_map(|control| control.evidence.iter()) {
+            if !kinds.contains(kind) {
+                kinds.push(*kind);
+            }
+        }
+
+        let tenants = match tenant_id {
+            Some(tenant_id) => vec![tenant_id.to_string()],
+            None => self.audit_repository.list_audited_tenants().await?,
+        };
+
+        let mut evidence = Vec::with_capacity(kinds.len());
+        for kind in kinds {
+            let mut items = Vec::with_capacity(tenants.len());
+            for tenant in &tenants {
+                items.push((tenant.clone(), self.collect(kind, tenant, period_start, period_end).await?));
+            }
+
+            evidence.push(match tenant_id {
+                Some(_) => items.remove(0).1,
+                None => Self::combine(kind, items),
+            });
+        }
+
+        Ok(evidence)
+    }
+
+    /// Platform evidence passes when it passes for every tenant
+    fn combine(kind: EvidenceKind, items: Vec<(String, EvidenceItem)>) -> EvidenceItem {
+        let failing = items.iter().filter(|(_, item)| !item.passed).count();
+        let tenants: serde_json::Map<String, Value> = items.into_iter()
+            .map(|(tenant_id, item)| (tenant_id, json!({
+                "passed": item.passed,
+                "summary": item.summary,
+                "data": item.data,
+            })))
+            .collect();
+
+        EvidenceItem {
+            kind,
+            passed: failing == 0,
+            summary: format!("{} of {} tenants failing", failing, tenants.len()),
+            data: json!({ "tenants": tenants }),
+            collected_at: Utc::now(),
+        }
+    }
+
+    async fn collect(
+        &self,
+        kind: EvidenceKind,
+        tenant_id: &str,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> SecurityResult<EvidenceItem> {
+        let (passed, summary, data) = match kind {
+            EvidenceKind::AccessReviews => self.access_reviews(tenant_id, period_start, period_end).await?,
+            EvidenceKind::RetentionStatus => self.retention_status(tenant_id).await?,
+            EvidenceKind::EncryptionPosture => self.encryption_posture(tenant_id).await?,
+            EvidenceKind::AuditSamples => self.audit_samples(tenant_id, period_start, period_end).await?,
+            EvidenceKind::SecurityAlerts => self.security_alerts(tenant_id, period_start, period_end).await?,
+        };
+
+        Ok(EvidenceItem {
+            kind,
+            passed,
+            summary,
+            data,
+            collected_at: Utc::now(),
+        })
+    }
+
+    async fn access_reviews(
+        &self,
+        tenant_id: &str,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> SecurityResult<(bool, String, Value)> {
+        let actions: Vec<String> = ESCALATION_ACTIONS.iter().map(|action| action.to_string()).collect();
+        let grants = self.detection_repository
+            .count_events(tenant_id, None, None, None, Some(&actions), Some(AuditOutcome::Success), period_start, period_end)
+            .await?;
+
+        let alerts: Vec<_> = self.detection_repository.alert_counts(tenant_id, period_start, period_end).await?
+            .into_iter()
+            .filter(|count| count.rule == AlertRule::PrivilegeEscalation)
+            .collect();
+        let escalation_alerts: i64 = alerts.iter().map(|count| count.count).sum();
+        let untriaged: i64 = alerts.iter().filter(|count| count.status == AlertStatus::Open).map(|count| count.count).sum();
+
+        // Campaigns completed in the period, referenced by their archived evidence's hash
+        let campaigns: Vec<Value> = self.access_review_repository
+            .list_completed_campaigns(tenant_id, period_start, period_end)
+            .await?
+            .into_iter()
+            .map(|campaign| json!({
+                "campaign_id": campaign.id,
+                "name": campaign.name,
+                "completed_at": campaign.completed_at,
+                "summary": campaign.evidence.as_ref().map(|evidence| evidence["summary"].clone()),
+                "evidence_hash": campaign.evidence_hash,
+            }))
+            .collect();
+        let revocation_failures: i64 = campaigns
+            .iter()
+            .filter_map(|campaign| campaign["summary"]["revocation_failures"].as_i64())
+            .sum();
+
+        Ok((
+            untriaged == 0 && revocation_failures == 0,
+            format!(
+                "{} privileged grants, {} escalation alerts, {} untriaged, {} access reviews completed",
+                grants, escalation_alerts, untriaged, campaigns.len()
+            ),
+            json!({
+                "privileged_grants": grants,
+                "escalation_alerts": escalation_alerts,
+                "untriaged_escalation_alerts": untriaged,
+                "alerts": alerts,
+                "access_review_campaigns": campaigns,
+                "failed_revocations": revocation_failures,
+            }),
+        ))
+    }
+
+    async fn retention_status(&self, tenant_id: &str) -> SecurityResult<(bool, String, Value)> {
+        let data_retention = self.retention_service.get_retention_summary(tenant_id).await?;
+        let audit_retention = self.audit_service.get_retention_settings(tenant_id, self.audit_retention_days).await?;
+
+        Ok((
+            data_retention.active_policies > 0,
+            format!(
+                "{} active retention policies, audit events kept {} days",
+                data_retention.active_policies, audit_retention.retention_days
+            ),
+            json!({
+                "data_retention": data_retention,
+                "audit_retention": audit_retention,
+            }),
+        ))
+    }
+
+    async fn encryption_posture(&self, tenant_id: &str) -> SecurityResult<(bool, String, Value)> {
+        let status = self.encryption.get_encryption_status(tenant_id).await?;
+        let strong_algorithm = status.algorithm.contains("256");
+        let rotated = status.key_rotation_days > 0 && status.key_rotation_days <= MAX_KEY_ROTATION_DAYS;
+
+        Ok((
+            strong_algorithm && rotated && self.audit_encryption_enabled,
+            format!(
+                "{} with keys rotated every {} days, audit log encryption {}",
+                status.algorithm,
+                status.key_rotation_days,
+                if self.audit_encryption_enabled { "on" } else { "off" }
+            ),
+            json!({
+                "encryption": status,
+                "audit_log_encryption": self.audit_encryption_enabled,
+            }),
+        ))
+    }
+
+    async fn audit_samples(
+        &self,
+        tenant_id: &str,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> SecurityResult<(bool, String, Value)> {
+        let filter = AuditLogFilter {
+            start_date: Some(period_start),
+            end_date: Some(period_end),
+            ..Default::default()
+        };
+        let total = self.audit_repository.count_audit_logs(tenant_id, &filter).await?;
+        let verification = self.audit_service.verify_chain(tenant_id).await?;
+
+        // Details can hold personal data, so the sample shows what happened
+        // and its place in the chain only
+        let sample: Vec<Value> = self.audit_repository
+            .sample_audit_logs(tenant_id, period_start, period_end, AUDIT_SAMPLE_SIZE)
+            .await?
+            .into_iter()
+            .map(|log| json!({
+                "id": log.id,
+                "service": log.service,
+                "user_id": log.user_id,
+                "event_category": log.event_category,
+                "resource_type": log.resource_type,
+                "resource_id": log.resource_id,
+                "action": log.action,
+                "outcome": log.outcome,
+                "created_at": log.created_at,
+                "sequence": log.sequence,
+                "hash": log.hash,
+            }))
+            .collect();
+
+        Ok((
+            total > 0 && verification.valid,
+            format!(
+                "{} audit events in the period, chain {}",
+                total,
+                if verification.valid { "intact" } else { "broken" }
+            ),
+            json!({
+                "total_events": total,
+                "chain_verification": verification,
+                "sample": sample,
+            }),
+        ))
+    }
+
+    async fn security_alerts(
+        &self,
+        tenant_id: &str,
+        period_start: DateTime<Utc>,
+        period_end: DateTime<Utc>,
+    ) -> SecurityResult<(bool, String, Value)> {
+        let counts = self.detection_repository.alert_counts(tenant_id, period_start, period_end).await?;
+        let total: i64 = counts.iter().map(|count| count.count).sum();
+        let open_serious: i64 = counts.iter()
+            .filter(|count| count.status.is_active())
+            .filter(|count| matches!(count.severity, SecurityEventSeverity::High | SecurityEventSeverity::Critical))
+            .map(|count| count.count)
+            .sum();
+
+        Ok((
+            open_serious == 0,
+            format!("{} alerts raised, {} high or critical still open", total, open_serious),
+            json!({
+                "total_alerts": total,
+                "open_high_or_critical": open_serious,
+                "alerts": counts,
+            }),
+        ))
+    }
+
+    pub async fn store_report(
+        &self,
+        tenant_id: Option<&str>,
+        template: &ComplianceTemplate,
+        request: &GenerateComplianceReportRequest,
+        assessment: ComplianceAssessment,
+        evidence: Vec<EvidenceItem>,
+    ) -> SecurityResult<ComplianceReport> {
+        let now = Utc::now();
+        let failing_controls: Vec<&ControlResult> = assessment.controls.iter().filter(|control| !control.passed).collect();
+
+        let report = self.repository.create_report(ComplianceReport {
+            id: Uuid::new_v4(),
+            tenant_id: tenant_id.unwrap_or(PLATFORM_SCOPE_ID).to_string(),
+            report_type: request.report_type.clone(),
+            period_start: request.period_start,
+            period_end: request.period_end,
+            status: assessment.status.clone(),
+            findings: serde_json::to_value(&failing_controls)?,
+            recommendations: serde_json::to_value(&assessment.recommendations)?,
+            risk_level: assessment.risk_level.clone(),
+            generated_by: request.requested_by.clone(),
+            created_at: now,
+            updated_at: now,
+            scope: if tenant_id.is_some() { "tenant" } else { "platform" }.to_string(),
+            template_version: Some(template.version.clone()),
+            compliance_score: Some(assessment.compliance_score),
+            controls: serde_json::to_value(&assessment.controls)?,
+            evidence: serde_json::to_value(&evidence)?,
+        }).await?;
+
+        info!(
+            tenant_id = %report.tenant_id,
+            report_id = %report.id,
+            framework = %template.name,
+            score = assessment.compliance_score,
+            "Compliance report generated"
+        );
+
+        self.audit_service.log_compliance_event(
+            &report.tenant_id,
+            &template.name,
+            "compliance_report_generated",
+            AuditOutcome::Success,
+            json!({
+                "report_id": report.id,
+                "scope": report.scope,
+                "period_start": report.period_start,
+                "period_end": report.period_end,
+                "compliance_score": assessment.compliance_score,
+                "generated_by": report.generated_by,
+            }),
+        ).await?;
+
+        Ok(report)
+    }
+
+    pub async fn list_reports(
+        &self,
+        tenant_id: Option<&str>,
+        report_type: Option<ComplianceReportType>,
+    ) -> SecurityResult<Vec<ComplianceReport>> {
+        self.repository
+            .list_reports(tenant_id.unwrap_or(PLATFORM_SCOPE_ID), report_type, REPORT_LIST_LIMIT)
+            .await
+    }
+
+    pub async fn get_report(&self, tenant_id: Option<&str>, report_id: Uuid) -> SecurityResult<ComplianceReport> {
+        self.repository
+            .get_report(tenant_id.unwrap_or(PLATFORM_SCOPE_ID), report_id)
+            .await?
+            .ok_or_else(|| SecurityError::NotFound(format!("Compliance report {} not found", report_id)))
+    }
+
+    /// A report as a file for auditors: the full report as JSON, or its
+    /// controls as CSV
+    pub async fn export_report(
+        &self,
+        tenant_id: Option<&str>,
+        report_id: Uuid,
+        format: &str,
+    ) -> SecurityResult<Vec<u8>> {
+        let report = self.get_report(tenant_id, report_id).await?;
+
+        match format.to_lowercase().as_str() {
+            "json" => Ok(serde_json::to_string_pretty(&report)?.into_bytes()),
+            "csv" => {
+                let controls: Vec<ControlResult> = serde_json::from_value(report.controls)?;
+                let mut csv_content = String::from("control_id,title,passed,failing_evidence\n");
+                for control in controls {
+                    let failing: Vec<String> = control.failing_evidence.iter()
+                        .map(|kind| serde_json::to_value(kind).ok().and_then(|v| v.as_str().map(str::to_string)).unwrap_or_default())
+                        .collect();
+                    csv_content.push_str(&format!(
+                        "\"{}\",\"{}\",{},\"{}\"\n",
+                        control.id.replace('"', "\"\""),
+                        control.title.replace('"', "\"\""),
+                        control.passed,
+                        failing.join(";")
+                    ));
+                }
+                Ok(csv_content.into_bytes())
+            }
+            _ => Err(SecurityError::Validation("Unsupported export format".to_string())),
+        }
+    }
+}
@@ -12,6 +12,10 @@ pub struct SecurityConfig {
     pub encryption: EncryptionConfig,
     pub scanning: ScanningConfig,
     pub zero_trust: ZeroTrustConfig,
+    pub detection: DetectionConfig,
+    pub key_rotation: KeyRotationConfig,
+    pub access_review: AccessReviewConfig,
+    pub incident: IncidentConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -49,6 +53,8 @@ pub struct AuditConfig {
     pub flush_interval_seconds: u32,
     pub storage_backend: String,
     pub encryption_enabled: bool,
+    /// How often tenants' audit events are checked against their retention
+    pub retention_check_interval_hours: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -59,6 +65,7 @@ pub struct ComplianceConfig {
     pub export_format: String,
     pub notification_email: String,
     pub compliance_officer_email: String,
+    pub destruction_signing_key: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -91,6 +98,62 @@ pub struct ZeroTrustConfig {
     pub device_verification: bool,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DetectionConfig {
+    pub enabled: bool,
+    /// How often new audit events are evaluated
+    pub interval_seconds: u64,
+    /// Downloads by one user within the window that always raise an alert
+    pub mass_download_threshold: i64,
+    pub mass_download_window_minutes: i64,
+    /// Failed sign-ins for one user or address within the window
+    pub failed_login_threshold: i64,
+    pub failed_login_window_minutes: i64,
+    /// History a user's usual activity is measured over
+    pub baseline_days: i64,
+    /// Standard deviations above the usual rate before activity is anomalous
+    pub baseline_sigma: f64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyRotationConfig {
+    pub enabled: bool,
+    /// How often due rotations and closed overlap windows are looked for
+    pub check_interval_seconds: u64,
+    /// How long a staged key is published before it signs anything; must
+    /// exceed how long validators cache key sets
+    pub propagation_minutes: i64,
+    /// Longer than an access token lives, so every token issued with the old key expires first
+    pub jwt_overlap_hours: i32,
+    pub webhook_overlap_hours: i32,
+    pub data_key_overlap_hours: i32,
+    /// Where rotated webhook secrets are delivered
+    pub tenant_service_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessReviewConfig {
+    pub enabled: bool,
+    /// How often scheduled campaigns are started and expired ones closed
+    pub check_interval_seconds: u64,
+    /// How long reviewers have when a campaign does not say
+    pub default_review_days: i32,
+    /// Where user roles are listed and revoked
+    pub user_service_url: String,
+    /// Where module grants are listed and revoked
+    pub module_service_url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentConfig {
+    /// Where sessions are revoked and API keys disabled
+    pub auth_service_url: String,
+    /// Where module instances are quarantined
+    pub module_service_url: String,
+    /// Where tenants are locked
+    pub tenant_service_url: String,
+}
+
 impl SecurityConfig {
     pub fn from_env() -> Result<Self> {
         Ok(Self {
@@ -156,6 +219,9 @@ impl SecurityConfig {
                 encryption_enabled: env::var("AUDIT_ENCRYPTION_ENABLED")
                     .unwrap_or_else(|_| "true".to_string())
                     .parse()?,
+                retention_check_interval_hours: env::var("AUDIT_RETENTION_CHECK_INTERVAL_HOURS")
+                    .unwrap_or_else(|_| "24".to_string())
+                    .parse()?,
             },
             compliance: ComplianceConfig {
                 gdpr_enabled: env::var("GDPR_ENABLED")
@@ -173,6 +239,8 @@ impl SecurityConfig {
                     .unwrap_or_else(|_| "compliance@adxcore.com".to_string()),
                 compliance_officer_email: env::var("COMPLIANCE_OFFICER_EMAIL")
                     .unwrap_or_else(|_| "dpo@adxcore.com".to_string()),
+                destruction_signing_key: env::var("DESTRUCTION_CERT_SIGNING_KEY")
+                    .unwrap_or_else(|_| "adx-core-destruction-signing-key".to_string()),
             },
             encryption: EncryptionConfig {
                 algorithm: env::var("ENCRYPTION_ALGORITHM")
@@ -227,6 +295,77 @@ impl SecurityConfig {
                     .unwrap_or_else(|_| "true".to_string())
                     .parse()?,
             },
+            detection: DetectionConfig {
+                enabled: env::var("DETECTION_ENABLED")
+                    .unwrap_or_else(|_| "true".to_string())
+                    .parse()?,
+                interval_seconds: env::var("DETECTION_INTERVAL_SECONDS")
+                    .unwrap_or_else(|_| "60".to_string())
+                    .parse()?,
+                mass_download_threshold: env::var("DETECTION_MASS_DOWNLOAD_THRESHOLD")
+                    .unwrap_or_else(|_| "200".to_string())
+                    .parse()?,
+                mass_download_window_minutes: env::var("DETECTION_MASS_DOWNLOAD_WINDOW_MINUTES")
+                    .unwrap_or_else(|_| "15".to_string())
+                    .parse()?,
+                failed_login_threshold: env::var("DETECTION_FAILED_LOGIN_THRESHOLD")
+                    .unwrap_or_else(|_| "10".to_string())
+                    .parse()?,
+                failed_login_window_minutes: env::var("DETECTION_FAILED_LOGIN_WINDOW_MINUTES")
+                    .unwrap_or_else(|_| "15".to_string())
+                    .parse()?,
+                baseline_days: env::var("DETECTION_BASELINE_DAYS")
+                    .unwrap_or_else(|_| "14".to_string())
+                    .parse()?,
+                baseline_sigma: env::var("DETECTION_BASELINE_SIGMA")
+                    .unwrap_or_else(|_| "4.0".to_string())
+                    .parse()?,
+            },
+            key_rotation: KeyRotationConfig {
+                enabled: env::var("KEY_ROTATION_ENABLED")
+                    .unwrap_or_else(|_| "true".to_string())
+                    .parse()?,
+                check_interval_seconds: env::var("KEY_ROTATION_CHECK_INTERVAL_SECONDS")
+                    .unwrap_or_else(|_| "300".to_string())
+                    .parse()?,
+                propagation_minutes: env::var("KEY_ROTATION_PROPAGATION_MINUTES")
+                    .unwrap_or_else(|_| "10".to_string())
+                    .parse()?,
+                jwt_overlap_hours: env::var("KEY_ROTATION_JWT_OVERLAP_HOURS")
+                    .unwrap_or_else(|_| "25".to_string())
+                    .parse()?,
+                webhook_overlap_hours: env::var("KEY_ROTATION_WEBHOOK_OVERLAP_HOURS")
+                    .unwrap_or_else(|_| "24".to_string())
+                    .parse()?,
+                data_key_overlap_hours: env::var("KEY_ROTATION_DATA_KEY_OVERLAP_HOURS")
+                    .unwrap_or_else(|_| "24".to_string())
+                    .parse()?,
+                tenant_service_url: env::var("TENANT_SERVICE_URL")
+                    .unwrap_or_else(|_| "http://localhost:8085".to_string()),
+            },
+            access_review: AccessReviewConfig {
+                enabled: env::var("ACCESS_REVIEW_ENABLED")
+                    .unwrap_or_else(|_| "true".to_string())
+                    .parse()?,
+                check_interval_seconds: env::var("ACCESS_REVIEW_CHECK_INTERVAL_SECONDS")
+                    .unwrap_or_else(|_| "300".to_string())
+                    .parse()?,
+                default_review_days: env::var("ACCESS_REVIEW_DEFAULT_REVIEW_DAYS")
+                    .unwrap_or_else(|_| "14".to_string())
+                    .parse()?,
+                user_service_url: env::var("USER_SERVICE_URL")
+                    .unwrap_or_else(|_| "http://localhost:8082".to_string()),
+                module_service_url: env::var("MODULE_SERVICE_URL")
+                    .unwrap_or_else(|_| "http://localhost:8086".to_string()),
+            },
+            incident: IncidentConfig {
+                auth_service_url: env::var("AUTH_SERVICE_URL")
+                    .unwrap_or_else(|_| "http://localhost:8081".to_string()),
+                module_service_url: env::var("MODULE_SERVICE_URL")
+                    .unwrap_or_else(|_| "http://localhost:8086".to_string()),
+                tenant_service_url: env::var("TENANT_SERVICE_URL")
+                    .unwrap_or_else(|_| "http://localhost:8085".to_string()),
+            },
         })
     }
 }
\ No newline at end of file
@@ -0,0 +1,242 @@
+use crate::{
+    error::{SecurityError, SecurityResult},
+    models::{
+        AuditOutcome, DataDestructionCertificate, DestructionCertificateVerification,
+        IssueDestructionCertificateRequest, PurgedServiceRecord,
+    },
+    repositories::DestructionCertificateRepository,
+    audit::AuditService,
+};
+use chrono::{DateTime, SubsecRound, Utc};
+use ring::{
+    digest,
+    signature::{Ed25519KeyPair, KeyPair, UnparsedPublicKey, ED25519},
+};
+use serde::Serialize;
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Fields covered by the certificate signature, in a fixed order so the
+/// digest can be recomputed from a stored certificate.
+#[derive(Serialize)]
+struct CertificateBody<'a> {
+    certificate_id: Uuid,
+    tenant_id: &'a str,
+    tenant_name: &'a str,
+    requested_by: &'a str,
+    soft_deleted_at: DateTime<Utc>,
+    purged_at: DateTime<Utc>,
+    purged_services: &'a [PurgedServiceRecord],
+    issued_at: DateTime<Utc>,
+}
+
+/// Ed25519 key used to sign data destruction certificates
+pub struct DestructionCertificateSigner {
+    key_pair: Ed25519KeyPair,
+    key_id: String,
+}
+
+impl DestructionCertificateSigner {
+    /// Derive the signing key from the configured secret. In production the
+    /// secret would come from the KMS like the encryption master key.
+    pub fn from_secret(secret: &str) -> SecurityResult<Self> {
+        if secret.is_empty() {
+            return Err(SecurityError::Encryption("Destruction certificate signing key is empty".to_string()));
+        }
+
+        let seed = digest::digest(&digest::SHA256, secret.as_bytes());
+        let key_pair = Ed25519KeyPair::from_seed_unchecked(seed.as_ref())
+            .map_err(|_| SecurityError::Encryption("Invalid destruction certificate signing key".to_string()))?;
+
+        let key_id = hex::encode(&digest::digest(&digest::SHA256, key_pair.public_key().as_ref()).as_ref()[..8]);
+
+        Ok(Self { key_pair, key_id })
+    }
+
+    pub fn key_id(&self) -> &str {
+        &self.key_id
+    }
+
+    /// Hex public key that auditors can use to check certificates offline
+    pub fn public_key(&self) -> String {
+        hex::encode(self.key_pair.public_key().as_ref())
+    }
+
+    /// Returns the hex digest and hex signature of the certificate body
+    fn sign(&self, body: &CertificateBody<'_>) -> SecurityResult<(String, String)> {
+        let digest = Self::digest(body)?;
+        let signature = self.key_pair.sign(&digest);
+        Ok((hex::encode(digest), hex::encode(signature.as_ref())))
+    }
+
+    fn verify(&self, body: &CertificateBody<'_>, digest_hex: &str, signature_hex: &str) -> bool {
+        let digest = match Self::digest(body) {
+            Ok(digest) => digest,
+            Err(_) => return false,
+        };
+        if hex::encode(&digest) != digest_hex {
+            return false;
+        }
+
+        let signature = match hex::decode(signature_hex) {
+            Ok(signature) => signature,
+            Err(_) => return false,
+        };
+
+        UnparsedPublicKey::new(&ED25519, self.key_pair.public_key().as_ref())
+            .verify(&digest, &signature)
+            .is_ok()
+    }
+
+    fn digest(body: &CertificateBody<'_>) -> SecurityResult<Vec<u8>> {
+        let bytes = serde_json::to_vec(body)?;
+        Ok(digest::digest(&digest::SHA256, &bytes).as_ref().to_vec())
+    }
+}
+
+#[derive(Clone)]
+pub struct DestructionCertificateService {
+    repository: Arc<DestructionCertificateRepository>,
+    audit_service: Arc<AuditService>,
+    signer: Arc<DestructionCertificateSigner>,
+}
+
+impl DestructionCertificateService {
+    pub fn new(
+        repository: Arc<DestructionCertificateRepository>,
+        audit_service: Arc<AuditService>,
+        signer: Arc<DestructionCertificateSigner>,
+    ) -> Self {
+        Self {
+            repository,
+            audit_service,
+            signer,
+        }
+    }
+
+    /// Sign and store the certificate for a purged tenant. Issuing twice with
+    /// the same certificate id returns the stored certificate unchanged.
+    pub async fn issue_certificate(
+        &self,
+        request: IssueDestructionCertificateRequest,
+    ) -> SecurityResult<DataDestructionCertificate> {
+        if let Some(existing) = self.repository.get_certificate(request.certificate_id).await? {
+            if existing.tenant_id != request.tenant_id {
+                return Err(SecurityError::Conflict(format!(
+                    "Certificate {} was issued for another tenant",
+                    request.certificate_id
+                )));
+            }
+            return Ok(existing);
+        }
+
+        if request.purged_services.is_empty() {
+            return Err(SecurityError::Validation("At least one purged service is required".to_string()));
+        }
+
+        // Timestamps are cut to the microsecond precision Postgres stores so
+        // the digest still matches after the certificate is read back
+        let mut purged_services = request.purged_services;
+        for record in &mut purged_services {
+            record.purged_at = record.purged_at.trunc_subsecs(6);
+        }
+        purged_services.sort_by(|a, b| a.service.cmp(&b.service));
+
+        let soft_deleted_at = request.soft_deleted_at.trunc_subsecs(6);
+        let purged_at = request.purged_at.trunc_subsecs(6);
+        let issued_at = Utc::now().trunc_subsecs(6);
+        let body = CertificateBody {
+            certificate_id: request.certificate_id,
+            tenant_id: &request.tenant_id,
+            tenant_name: &request.tenant_name,
+            requested_by: &request.requested_by,
+            soft_deleted_at,
+            purged_at,
+            purged_services: &purged_services,
+            issued_at,
+        };
+        let (digest, signature) = self.signer.sign(&body)?;
+
+        let certificate = self.repository.create_certificate(DataDestructionCertificate {
+            id: request.certificate_id,
+            tenant_id: request.tenant_id.clone(),
+            tenant_name: request.tenant_name,
+            requested_by: request.requested_by,
+            soft_deleted_at,
+            purged_at,
+            purged_services: serde_json::to_value(&purged_services)?,
+            digest,
+            signature,
+            signing_key_id: self.signer.key_id().to_string(),
+            issued_at,
+        }).await?;
+
+        self.audit_service.log_compliance_event(
+            &certificate.tenant_id,
+            "DATA_DESTRUCTION",
+            "destruction_certificate_issued",
+            AuditOutcome::Success,
+            serde_json::json!({
+                "certificate_id": certificate.id,
+                "digest": certificate.digest,
+                "signing_key_id": certificate.signing_key_id,
+                "services": purged_services.iter().map(|s| s.service.as_str()).collect::<Vec<_>>(),
+            }),
+        ).await?;
+
+        info!(
+            tenant_id = %certificate.tenant_id,
+            certificate_id = %certificate.id,
+            "Issued data destruction certificate"
+        );
+
+        Ok(certificate)
+    }
+
+    pub async fn get_certificate(&self, certificate_id: Uuid) -> SecurityResult<DataDestructionCertificate> {
+        self.repository
+            .get_certificate(certificate_id)
+            .await?
+            .ok_or_else(|| SecurityError::NotFound(format!("Destruction certificate {} not found", certificate_id)))
+    }
+
+    pub async fn get_tenant_certificates(&self, tenant_id: &str) -> SecurityResult<Vec<DataDestructionCertificate>> {
+        self.repository.get_tenant_certificates(tenant_id).await
+    }
+
+    /// Recompute the digest from the stored fields and check the signature
+    pub async fn verify_certificate(&self, certificate_id: Uuid) -> SecurityResult<DestructionCertificateVerification> {
+        let certificate = self.get_certificate(certificate_id).await?;
+
+        let valid = if certificate.signing_key_id != self.signer.key_id() {
+            warn!(
+                certificate_id = %certificate.id,
+                signing_key_id = %certificate.signing_key_id,
+                "Certificate was signed with a key that is no longer loaded"
+            );
+            false
+        } else {
+            let purged_services: Vec<PurgedServiceRecord> =
+                serde_json::from_value(certificate.purged_services.clone())?;
+            let body = CertificateBody {
+                certificate_id: certificate.id,
+                tenant_id: &certificate.tenant_id,
+                tenant_name: &certificate.tenant_name,
+                requested_by: &certificate.requested_by,
+                soft_deleted_at: certificate.soft_deleted_at,
+                purged_at: certificate.purged_at,
+                purged_services: &purged_services,
+                issued_at: certificate.issued_at,
+            };
+            self.signer.verify(&body, &certificate.digest, &certificate.signature)
+        };
+
+        Ok(DestructionCertificateVerification {
+            certificate_id: certificate.id,
+            valid,
+            signing_key_id: certificate.signing_key_id,
+            public_key: self.signer.public_key(),
+        })
+    }
+}
@@ -0,0 +1,771 @@
+use crate::{
+    audit::{AuditService, SERVICE_NAME},
+    config::DetectionConfig,
+    error::{SecurityError, SecurityResult},
+    models::{
+        AcknowledgeAlertRequest, AlertRule, AlertStatus, AssignAlertRequest, AuditEventCategory,
+        AuditLog, AuditOutcome, CreateAuditLogRequest, ReopenAlertRequest, ResolveAlertRequest,
+        SecurityAlert, SecurityAlertFilter, SecurityAlertListResponse, SecurityEventSeverity,
+    },
+    repositories::{AuditRepository, DetectionRepository},
+};
+use chrono::{Duration, Utc};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Actions that hand data out of the platform
+const DOWNLOAD_ACTIONS: &[&str] = &["download", "file_download", "bulk_download", "export", "data_export"];
+
+/// Actions that widen what a user may do
+pub(crate) const ESCALATION_ACTIONS: &[&str] = &[
+    "role_assigned",
+    "assign_role",
+    "permission_granted",
+    "grant_permission",
+    "user_promoted",
+];
+
+/// Roles whose grant is always worth a look
+const PRIVILEGED_ROLES: &[&str] = &["admin", "owner", "super_admin", "tenant_admin", "security_admin"];
+
+/// Below this many downloads in the window a user's baseline is not consulted,
+/// so users with almost no history do not alert on their first few files
+const MIN_BASELINE_EVENTS: i64 = 20;
+
+/// Audit events evaluated per query
+const EVALUATION_PAGE_SIZE: i64 = 500;
+
+/// A user's usual hourly rate of some activity
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Baseline {
+    pub mean: f64,
+    pub stddev: f64,
+}
+
+impl Baseline {
+    /// From the counts of the hours that had activity, out of `hours` hours
+    /// observed; the remaining hours count as zero.
+    pub fn from_hourly_counts(counts: &[i64], hours: i64) -> Self {
+        let hours = hours.max(counts.len() as i64).max(1) as f64;
+        let total: i64 = counts.iter().sum();
+        let mean = total as f64 / hours;
+
+        let active_deviation: f64 = counts.iter().map(|count| (*count as f64 - mean).powi(2)).sum();
+        let idle_deviation = (hours - counts.len() as f64) * mean.powi(2);
+        let stddev = ((active_deviation + idle_deviation) / hours).sqrt();
+
+        Self { mean, stddev }
+    }
+
+    /// Most events expected within `window_minutes` before it is anomalous
+    pub fn limit(&self, sigma: f64, window_minutes: i64) -> f64 {
+        (self.mean + sigma * self.stddev) * window_minutes as f64 / 60.0
+    }
+}
+
+/// Something a rule matched, before it is raised or added to an alert
+#[derive(Debug, Clone)]
+struct Finding {
+    rule: AlertRule,
+    severity: SecurityEventSeverity,
+    title: String,
+    description: String,
+    dedup_key: String,
+    user_id: Option<String>,
+    details: Value,
+}
+
+fn severity_rank(severity: &SecurityEventSeverity) -> u8 {
+    match severity {
+        SecurityEventSeverity::Info => 0,
+        SecurityEventSeverity::Low => 1,
+        SecurityEventSeverity::Medium => 2,
+        SecurityEventSeverity::High => 3,
+        SecurityEventSeverity::Critical => 4,
+    }
+}
+
+fn severity_name(severity: &SecurityEventSeverity) -> &'static str {
+    match severity {
+        SecurityEventSeverity::Info => "info",
+        SecurityEventSeverity::Low => "low",
+        SecurityEventSeverity::Medium => "medium",
+        SecurityEventSeverity::High => "high",
+        SecurityEventSeverity::Critical => "critical",
+    }
+}
+
+/// The rules an audit event is evaluated against
+fn rules_for(log: &AuditLog) -> Vec<AlertRule> {
+    let action = log.action.to_lowercase();
+    let succeeded = matches!(log.outcome, AuditOutcome::Success);
+    let mut rules = Vec::new();
+
+    if DOWNLOAD_ACTIONS.contains(&action.as_str()) && succeeded {
+        rules.push(AlertRule::MassDownload);
+    }
+
+    if matches!(log.event_category, AuditEventCategory::Authorization | AuditEventCategory::Administrative)
+        && ESCALATION_ACTIONS.contains(&action.as_str())
+        && succeeded
+    {
+        rules.push(AlertRule::PrivilegeEscalation);
+    }
+
+    if matches!(log.event_category, AuditEventCategory::Authentication) {
+        match log.outcome {
+            AuditOutcome::Failure => rules.push(AlertRule::FailedLoginBurst),
+            AuditOutcome::Success => rules.push(AlertRule::NewLoginLocation),
+            _ => {}
+        }
+    }
+
+    rules
+}
+
+/// Whether `count` downloads in the window are few enough to skip the
+/// user's baseline: too few to judge, or over the threshold regardless
+fn skips_baseline(count: i64, threshold: i64) -> bool {
+    count < MIN_BASELINE_EVENTS || count >= threshold
+}
+
+/// Severity of `count` downloads in the window. `baseline_limit` is the most
+/// the user's baseline allows, when it was consulted.
+fn mass_download_severity(count: i64, threshold: i64, baseline_limit: Option<f64>) -> Option<SecurityEventSeverity> {
+    if count >= threshold * 2 {
+        Some(SecurityEventSeverity::Critical)
+    } else if count >= threshold {
+        Some(SecurityEventSeverity::High)
+    } else if count >= MIN_BASELINE_EVENTS && baseline_limit.map_or(false, |limit| count as f64 > limit) {
+        Some(SecurityEventSeverity::Medium)
+    } else {
+        None
+    }
+}
+
+fn failed_login_severity(count: i64, threshold: i64) -> Option<SecurityEventSeverity> {
+    if count >= threshold * 3 {
+        Some(SecurityEventSeverity::High)
+    } else if count >= threshold {
+        Some(SecurityEventSeverity::Medium)
+    } else {
+        None
+    }
+}
+
+/// A user's first sign-in has nothing to compare with
+fn is_new_login_location(prior_logins: i64, from_address: i64) -> bool {
+    prior_logins > 0 && from_address == 0
+}
+
+/// Evaluates stored audit events against detection rules and the users' own
+/// baselines, and runs the triage workflow of the alerts it raises
+#[derive(Clone)]
+pub struct DetectionService {
+    audit_repository: Arc<AuditRepository>,
+    repository: Arc<DetectionRepository>,
+    audit_service: Arc<AuditService>,
+    config: DetectionConfig,
+}
+
+impl DetectionService {
+    pub fn new(
+        audit_repository: Arc<AuditRepository>,
+        repository: Arc<DetectionRepository>,
+        audit_service: Arc<AuditService>,
+        config: DetectionConfig,
+    ) -> Self {
+        Self {
+            audit_repository,
+            repository,
+            audit_service,
+            config,
+        }
+    }
+
+    /// Evaluate every tenant's audit events stored since the last run.
+    /// Returns how many new alerts were raised.
+    pub async fn run(&self) -> SecurityResult<usize> {
+        let mut raised = 0;
+        for tenant_id in self.audit_repository.list_audited_tenants().await? {
+            raised += self.evaluate_tenant(&tenant_id).await?;
+        }
+        Ok(raised)
+    }
+
+    async fn evaluate_tenant(&self, tenant_id: &str) -> SecurityResult<usize> {
+        let mut checkpoint = self.repository.get_checkpoint(tenant_id).await?;
+        let mut raised = 0;
+
+        loop {
+            let segment = self.audit_repository
+                .get_chain_segment(tenant_id, checkpoint, EVALUATION_PAGE_SIZE)
+                .await?;
+            if segment.is_empty() {
+                break;
+            }
+
+            for log in self.audit_service.decrypt_audit_logs(segment).await? {
+                for finding in self.evaluate_event(&log).await? {
+                    if self.raise(&log, finding).await? {
+                        raised += 1;
+                    }
+                }
+                checkpoint = log.sequence.unwrap_or(checkpoint);
+            }
+
+            self.repository.set_checkpoint(tenant_id, checkpoint).await?;
+        }
+
+        Ok(raised)
+    }
+
+    async fn evaluate_event(&self, log: &AuditLog) -> SecurityResult<Vec<Finding>> {
+        let mut findings = Vec::new();
+        for rule in rules_for(log) {
+            let finding = match rule {
+                AlertRule::MassDownload => self.check_mass_download(log).await?,
+                AlertRule::PrivilegeEscalation => Self::check_privilege_escalation(log),
+                AlertRule::FailedLoginBurst => self.check_failed_logins(log).await?,
+                AlertRule::NewLoginLocation => self.check_login_location(log).await?,
+            };
+            findings.extend(finding);
+        }
+        Ok(findings)
+    }
+
+    async fn check_mass_download(&self, log: &AuditLog) -> SecurityResult<Option<Finding>> {
+        let Some(user_id) = log.user_id.as_deref() else {
+            return Ok(None);
+        };
+
+        let actions: Vec<String> = DOWNLOAD_ACTIONS.iter().map(|action| action.to_string()).collect();
+        let window_start = log.created_at - Duration::minutes(self.config.mass_download_window_minutes);
+        let count = self.repository
+            .count_events(&log.tenant_id, Some(user_id), None, None, Some(&actions), Some(AuditOutcome::Success), window_start, log.created_at)
+            .await?;
+
+        let threshold = self.config.mass_download_threshold;
+        let baseline_limit = if skips_baseline(count, threshold) {
+            None
+        } else {
+            let baseline_start = window_start - Duration::days(self.config.baseline_days);
+            let counts = self.repository
+                .hourly_event_counts(&log.tenant_id, user_id, &actions, baseline_start, window_start)
+                .await?;
+            let baseline = Baseline::from_hourly_counts(&counts, self.config.baseline_days * 24);
+            Some(baseline.limit(self.config.baseline_sigma, self.config.mass_download_window_minutes))
+        };
+        let Some(severity) = mass_download_severity(count, threshold, baseline_limit) else {
+            return Ok(None);
+        };
+
+        Ok(Some(Finding {
+            rule: AlertRule::MassDownload,
+            severity,
+            title: "Unusual volume of downloads".to_string(),
+            description: format!(
+                "User {} downloaded {} items within {} minutes",
+                user_id, count, self.config.mass_download_window_minutes
+            ),
+            dedup_key: format!("mass_download:{}", user_id),
+            user_id: Some(user_id.to_string()),
+            details: json!({
+                "download_count": count,
+                "window_minutes": self.config.mass_download_window_minutes,
+                "threshold": threshold,
+            }),
+        }))
+    }
+
+    fn check_privilege_escalation(log: &AuditLog) -> Option<Finding> {
+        let role = ["role", "role_name", "permission"]
+            .iter()
+            .find_map(|key| log.details.get(key).and_then(Value::as_str))
+            .map(str::to_lowercase);
+        let privileged = role.as_deref().map_or(false, |role| PRIVILEGED_ROLES.contains(&role));
+        let self_granted = log.user_id.is_some() && log.user_id == log.resource_id;
+
+        let severity = if self_granted {
+            SecurityEventSeverity::Critical
+        } else if privileged {
+            SecurityEventSeverity::High
+        } else {
+            return None;
+        };
+
+        let actor = log.user_id.as_deref().unwrap_or("unknown");
+        let grantee = log.resource_id.as_deref().unwrap_or("unknown");
+
+        Some(Finding {
+            rule: AlertRule::PrivilegeEscalation,
+            severity,
+            title: if self_granted {
+                "User granted privileges to themselves".to_string()
+            } else {
+                "Privileged role granted".to_string()
+            },
+            description: format!(
+                "{} granted {} to {}",
+                actor,
+                role.as_deref().unwrap_or("additional permissions"),
+                grantee
+            ),
+            dedup_key: format!("privilege_escalation:{}:{}", actor, grantee),
+            user_id: log.user_id.clone(),
+            details: json!({
+                "granted_by": actor,
+                "granted_to": grantee,
+                "role": role,
+                "self_granted": self_granted,
+            }),
+        })
+    }
+
+    async fn check_failed_logins(&self, log: &AuditLog) -> SecurityResult<Option<Finding>> {
+        // Failures without a known user are tracked by address
+        let (user_id, ip_address, subject) = match (log.user_id.as_deref(), log.ip_address.as_deref()) {
+            (Some(user_id), _) => (Some(user_id), None, user_id),
+            (None, Some(ip_address)) => (None, Some(ip_address), ip_address),
+            (None, None) => return Ok(None),
+        };
+
+        let window_start = log.created_at - Duration::minutes(self.config.failed_login_window_minutes);
+        let count = self.repository
+            .count_events(
+                &log.tenant_id,
+                user_id,
+                ip_address,
+                Some(AuditEventCategory::Authentication),
+                None,
+                Some(AuditOutcome::Failure),
+                window_start,
+                log.created_at,
+            )
+            .await?;
+
+        let Some(severity) = failed_login_severity(count, self.config.failed_login_threshold) else {
+            return Ok(None);
+        };
+
+        Ok(Some(Finding {
+            rule: AlertRule::FailedLoginBurst,
+            severity,
+            title: "Repeated failed sign-ins".to_string(),
+            description: format!(
+                "{} failed sign-ins for {} within {} minutes",
+                count, subject, self.config.failed_login_window_minutes
+            ),
+            dedup_key: format!("failed_logins:{}", subject),
+            user_id: user_id.map(str::to_string),
+            details: json!({
+                "failed_count": count,
+                "window_minutes": self.config.failed_login_window_minutes,
+                "ip_address": log.ip_address,
+            }),
+        }))
+    }
+
+    async fn check_login_location(&self, log: &AuditLog) -> SecurityResult<Option<Finding>> {
+        let (Some(user_id), Some(ip_address)) = (log.user_id.as_deref(), log.ip_address.as_deref()) else {
+            return Ok(None);
+        };
+
+        let baseline_start = log.created_at - Duration::days(self.config.baseline_days);
+        let (prior_logins, from_address) = self.repository
+            .prior_logins(&log.tenant_id, user_id, ip_address, baseline_start, log.created_at)
+            .await?;
+
+        if !is_new_login_location(prior_logins, from_address) {
+            return Ok(None);
+        }
+
+        let window_start = log.created_at - Duration::minutes(self.config.failed_login_window_minutes);
+        let recent_failures = self.repository
+            .count_events(
+                &log.tenant_id,
+                Some(user_id),
+                None,
+                Some(AuditEventCategory::Authentication),
+                None,
+                Some(AuditOutcome::Failure),
+                window_start,
+                log.created_at,
+            )
+            .await?;
+
+        Ok(Some(Finding {
+            rule: AlertRule::NewLoginLocation,
+            // Getting in from somewhere new right after failing is what a
+            // guessed password looks like
+            severity: if recent_failures > 0 {
+                SecurityEventSeverity::High
+            } else {
+                SecurityEventSeverity::Low
+            },
+            title: "Sign-in from a new address".to_string(),
+            description: format!("User {} signed in from {} for the first time", user_id, ip_address),
+            dedup_key: format!("new_login_location:{}:{}", user_id, ip_address),
+            user_id: Some(user_id.to_string()),
+            details: json!({
+                "ip_address": ip_address,
+                "user_agent": log.user_agent,
+                "recent_failures": recent_failures,
+                "prior_logins": prior_logins,
+            }),
+        }))
+    }
+
+    /// Raise a finding, or add it to the subject's active alert. Returns
+    /// whether a new alert was raised.
+    async fn raise(&self, log: &AuditLog, finding: Finding) -> SecurityResult<bool> {
+        if let Some(alert) = self.repository.find_active_alert(&log.tenant_id, &finding.dedup_key).await? {
+            let severity = if severity_rank(&finding.severity) > severity_rank(&alert.severity) {
+                finding.severity
+            } else {
+                alert.severity
+            };
+            self.repository
+                .record_alert_match(alert.id, log.id, log.created_at, severity, finding.details)
+                .await?;
+            return Ok(false);
+        }
+
+        let now = Utc::now();
+        let alert = self.repository.create_alert(SecurityAlert {
+            id: Uuid::new_v4(),
+            tenant_id: log.tenant_id.clone(),
+            rule: finding.rule,
+            severity: finding.severity,
+            status: AlertStatus::Open,
+            title: finding.title,
+            description: finding.description,
+            dedup_key: finding.dedup_key,
+            user_id: finding.user_id,
+            event_ids: json!([log.id]),
+            event_count: 1,
+            details: finding.details,
+            first_seen_at: log.created_at,
+            last_seen_at: log.created_at,
+            assigned_to: None,
+            assigned_at: None,
+            acknowledged_by: None,
+            acknowledged_at: None,
+            resolved_by: None,
+            resolved_at: None,
+            resolution_notes: None,
+            created_at: now,
+            updated_at: now,
+        }).await?;
+
+        warn!(
+            tenant_id = %alert.tenant_id,
+            alert_id = %alert.id,
+            rule = ?alert.rule,
+            severity = severity_name(&alert.severity),
+            "Security alert raised"
+        );
+
+        self.audit_service.log_security_event(
+            &alert.tenant_id,
+            "security_alert_raised",
+            severity_name(&alert.severity),
+            &alert.description,
+            json!({ "alert_id": alert.id, "rule": alert.rule, "event_id": log.id }),
+        ).await?;
+
+        Ok(true)
+    }
+
+    pub async fn list_alerts(
+        &self,
+        tenant_id: &str,
+        filter: &SecurityAlertFilter,
+        page: i32,
+        page_size: i32,
+    ) -> SecurityResult<SecurityAlertListResponse> {
+        let alerts = self.repository.list_alerts(tenant_id, filter, page, page_size).await?;
+        let total_count = self.repository.count_alerts(tenant_id, filter).await?;
+
+        Ok(SecurityAlertListResponse {
+            alerts,
+            total_count,
+            page,
+            page_size,
+        })
+    }
+
+    pub async fn get_alert(&self, tenant_id: &str, alert_id: Uuid) -> SecurityResult<SecurityAlert> {
+        self.repository
+            .get_alert(tenant_id, alert_id)
+            .await?
+            .ok_or_else(|| SecurityError::NotFound(format!("Security alert {} not found", alert_id)))
+    }
+
+    pub async fn acknowledge_alert(
+        &self,
+        tenant_id: &str,
+        alert_id: Uuid,
+        request: AcknowledgeAlertRequest,
+    ) -> SecurityResult<SecurityAlert> {
+        let mut alert = self.get_alert(tenant_id, alert_id).await?;
+        if alert.status != AlertStatus::Open {
+            return Err(SecurityError::Conflict("Only open alerts can be acknowledged".to_string()));
+        }
+
+        let now = Utc::now();
+        alert.status = AlertStatus::Acknowledged;
+        alert.acknowledged_by = Some(request.acknowledged_by.clone());
+        alert.acknowledged_at = Some(now);
+        alert.updated_at = now;
+
+        let alert = self.repository.update_alert(alert).await?;
+        self.audit_alert_change(&alert, "acknowledge_alert", &request.acknowledged_by, json!({})).await?;
+        Ok(alert)
+    }
+
+    pub async fn assign_alert(
+        &self,
+        tenant_id: &str,
+        alert_id: Uuid,
+        request: AssignAlertRequest,
+    ) -> SecurityResult<SecurityAlert> {
+        let mut alert = self.get_alert(tenant_id, alert_id).await?;
+        if !alert.status.is_active() {
+            return Err(SecurityError::Conflict("Closed alerts cannot be assigned".to_string()));
+        }
+
+        let now = Utc::now();
+        let previous_assignee = alert.assigned_to.replace(request.assigned_to.clone());
+        alert.assigned_at = Some(now);
+        alert.updated_at = now;
+
+        let alert = self.repository.update_alert(alert).await?;
+        self.audit_alert_change(
+            &alert,
+            "assign_alert",
+            &request.assigned_by,
+            json!({ "assigned_to": request.assigned_to, "previous_assignee": previous_assignee }),
+        ).await?;
+        Ok(alert)
+    }
+
+    pub async fn resolve_alert(
+        &self,
+        tenant_id: &str,
+        alert_id: Uuid,
+        request: ResolveAlertRequest,
+    ) -> SecurityResult<SecurityAlert> {
+        let mut alert = self.get_alert(tenant_id, alert_id).await?;
+        if !alert.status.is_active() {
+            return Err(SecurityError::Conflict("Alert is already closed".to_string()));
+        }
+
+        let now = Utc::now();
+        alert.status = if request.false_positive {
+            AlertStatus::FalsePositive
+        } else {
+            AlertStatus::Resolved
+        };
+        alert.resolved_by = Some(request.resolved_by.clone());
+        alert.resolved_at = Some(now);
+        alert.resolution_notes = request.notes.clone();
+        alert.updated_at = now;
+
+        let alert = self.repository.update_alert(alert).await?;
+        info!(tenant_id = %tenant_id, alert_id = %alert_id, status = ?alert.status, "Security alert closed");
+        self.audit_alert_change(
+            &alert,
+            "resolve_alert",
+            &request.resolved_by,
+            json!({ "status": alert.status, "notes": request.notes }),
+        ).await?;
+        Ok(alert)
+    }
+
+    pub async fn reopen_alert(
+        &self,
+        tenant_id: &str,
+        alert_id: Uuid,
+        request: ReopenAlertRequest,
+    ) -> SecurityResult<SecurityAlert> {
+        let mut alert = self.get_alert(tenant_id, alert_id).await?;
+        if alert.status.is_active() {
+            return Err(SecurityError::Conflict("Alert is not closed".to_string()));
+        }
+        // Matches after closing raise a fresh alert for the subject
+        if let Some(active) = self.repository.find_active_alert(tenant_id, &alert.dedup_key).await? {
+            return Err(SecurityError::Conflict(format!(
+                "Alert {} is already open for the same subject", active.id
+            )));
+        }
+
+        alert.status = AlertStatus::Open;
+        alert.resolved_by = None;
+        alert.resolved_at = None;
+        alert.updated_at = Utc::now();
+
+        let alert = self.repository.update_alert(alert).await?;
+        self.audit_alert_change(&alert, "reopen_alert", &request.reopened_by, json!({ "reason": request.reason })).await?;
+        Ok(alert)
+    }
+
+    async fn audit_alert_change(
+        &self,
+        alert: &SecurityAlert,
+        action: &str,
+        actor: &str,
+        details: Value,
+    ) -> SecurityResult<Uuid> {
+        self.audit_service.log_event(CreateAuditLogRequest {
+            event_id: None,
+            tenant_id: alert.tenant_id.clone(),
+            service: SERVICE_NAME.to_string(),
+            user_id: Some(actor.to_string()),
+            session_id: None,
+            event_type: "security_alert".to_string(),
+            event_category: AuditEventCategory::Security,
+            resource_type: "security_alert".to_string(),
+            resource_id: Some(alert.id.to_string()),
+            action: action.to_string(),
+            outcome: AuditOutcome::Success,
+            ip_address: None,
+            user_agent: None,
+            request_id: None,
+            details,
+            occurred_at: None,
+        }).await
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(category: AuditEventCategory, action: &str, outcome: AuditOutcome) -> AuditLog {
+        AuditLog {
+            id: Uuid::new_v4(),
+            tenant_id: "tenant-1".to_string(),
+            service: "user-service".to_string(),
+            user_id: Some("alice".to_string()),
+            session_id: None,
+            event_type: action.to_string(),
+            event_category: category,
+            resource_type: "user".to_string(),
+            resource_id: Some("bob".to_string()),
+            action: action.to_string(),
+            outcome,
+            ip_address: Some("203.0.113.7".to_string()),
+            user_agent: None,
+            request_id: None,
+            details: json!({}),
+            risk_score: None,
+            created_at: Utc::now(),
+            sequence: None,
+            previous_hash: None,
+            hash: None,
+        }
+    }
+
+    fn severity(severity: Option<SecurityEventSeverity>) -> Option<&'static str> {
+        severity.as_ref().map(severity_name)
+    }
+
+    #[test]
+    fn test_rules_for_events() {
+        let download = event(AuditEventCategory::DataAccess, "File_Download", AuditOutcome::Success);
+        assert_eq!(rules_for(&download), vec![AlertRule::MassDownload]);
+
+        let grant = event(AuditEventCategory::Authorization, "role_assigned", AuditOutcome::Success);
+        assert_eq!(rules_for(&grant), vec![AlertRule::PrivilegeEscalation]);
+
+        let failed_login = event(AuditEventCategory::Authentication, "login", AuditOutcome::Failure);
+        assert_eq!(rules_for(&failed_login), vec![AlertRule::FailedLoginBurst]);
+
+        let login = event(AuditEventCategory::Authentication, "login", AuditOutcome::Success);
+        assert_eq!(rules_for(&login), vec![AlertRule::NewLoginLocation]);
+    }
+
+    #[test]
+    fn test_rules_for_ignores_unmatched_events() {
+        // Failed downloads and grants changed nothing
+        assert!(rules_for(&event(AuditEventCategory::DataAccess, "download", AuditOutcome::Failure)).is_empty());
+        assert!(rules_for(&event(AuditEventCategory::Authorization, "role_assigned", AuditOutcome::Error)).is_empty());
+        // Grants only count as authorization or administrative events
+        assert!(rules_for(&event(AuditEventCategory::DataModification, "role_assigned", AuditOutcome::Success)).is_empty());
+        assert!(rules_for(&event(AuditEventCategory::Authentication, "login", AuditOutcome::Warning)).is_empty());
+        assert!(rules_for(&event(AuditEventCategory::DataAccess, "view", AuditOutcome::Success)).is_empty());
+    }
+
+    #[test]
+    fn test_mass_download_severity() {
+        assert_eq!(severity(mass_download_severity(400, 200, None)), Some("critical"));
+        assert_eq!(severity(mass_download_severity(200, 200, None)), Some("high"));
+        assert_eq!(severity(mass_download_severity(50, 200, Some(30.0))), Some("medium"));
+        assert_eq!(severity(mass_download_severity(50, 200, Some(50.0))), None);
+        // Too few to judge against a baseline, however quiet the user is
+        assert_eq!(severity(mass_download_severity(MIN_BASELINE_EVENTS - 1, 200, Some(0.0))), None);
+    }
+
+    #[test]
+    fn test_skips_baseline() {
+        assert!(skips_baseline(MIN_BASELINE_EVENTS - 1, 200));
+        assert!(skips_baseline(200, 200));
+        assert!(!skips_baseline(MIN_BASELINE_EVENTS, 200));
+    }
+
+    #[test]
+    fn test_baseline_counts_idle_hours() {
+        // 24 downloads in one hour out of 24 observed
+        let baseline = Baseline::from_hourly_counts(&[24], 24);
+        assert_eq!(baseline.mean, 1.0);
+        assert!((baseline.stddev - 23f64.sqrt()).abs() < 1e-9);
+
+        let steady = Baseline::from_hourly_counts(&[4, 4, 4, 4], 4);
+        assert_eq!(steady.stddev, 0.0);
+        assert_eq!(steady.limit(3.0, 15), 1.0);
+    }
+
+    #[test]
+    fn test_privilege_escalation() {
+        let mut grant = event(AuditEventCategory::Authorization, "role_assigned", AuditOutcome::Success);
+        grant.details = json!({ "role": "Admin" });
+        let finding = DetectionService::check_privilege_escalation(&grant).unwrap();
+        assert_eq!(severity_name(&finding.severity), "high");
+        assert_eq!(finding.dedup_key, "privilege_escalation:alice:bob");
+
+        grant.resource_id = Some("alice".to_string());
+        grant.details = json!({ "role": "viewer" });
+        let finding = DetectionService::check_privilege_escalation(&grant).unwrap();
+        assert_eq!(severity_name(&finding.severity), "critical");
+        assert_eq!(finding.details["self_granted"], json!(true));
+    }
+
+    #[test]
+    fn test_ordinary_grant_is_not_escalation() {
+        let mut grant = event(AuditEventCategory::Authorization, "role_assigned", AuditOutcome::Success);
+        grant.details = json!({ "role": "viewer" });
+        assert!(DetectionService::check_privilege_escalation(&grant).is_none());
+
+        // Nobody granting to nobody is not a self-grant
+        grant.user_id = None;
+        grant.resource_id = None;
+        assert!(DetectionService::check_privilege_escalation(&grant).is_none());
+    }
+
+    #[test]
+    fn test_failed_login_severity() {
+        assert_eq!(severity(failed_login_severity(4, 5)), None);
+        assert_eq!(severity(failed_login_severity(5, 5)), Some("medium"));
+        assert_eq!(severity(failed_login_severity(15, 5)), Some("high"));
+    }
+
+    #[test]
+    fn test_new_login_location() {
+        assert!(is_new_login_location(12, 0));
+        assert!(!is_new_login_location(12, 3));
+        assert!(!is_new_login_location(0, 0));
+    }
+}
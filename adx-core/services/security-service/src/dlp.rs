@@ -0,0 +1,111 @@
+use crate::{
+    audit::AuditService,
+    error::{SecurityError, SecurityResult},
+    models::{AuditLogFilter, AuditLogResponse, AuditOutcome, DlpPolicyRecord, SetDlpPolicyRequest},
+    repositories::DlpRepository,
+};
+use adx_shared::dlp::{DlpPolicy, DLP_INCIDENT_ACTION};
+use adx_shared::ServiceError;
+use serde_json::json;
+use std::sync::Arc;
+
+// DLP policies. Security-service only stores and validates them; scanning
+// happens in the services that receive the content, which fetch the policy
+// through adx_shared::dlp::DlpClient and report incidents to the audit log.
+
+pub const MAX_DLP_RULES: usize = 100;
+
+pub struct DlpPolicyService {
+    repository: Arc<DlpRepository>,
+    audit_service: Arc<AuditService>,
+}
+
+impl DlpPolicyService {
+    pub fn new(repository: Arc<DlpRepository>, audit_service: Arc<AuditService>) -> Self {
+        Self {
+            repository,
+            audit_service,
+        }
+    }
+
+    /// The tenant's policy; a disabled one when it has not set any
+    pub async fn get_policy(&self, tenant_id: &str) -> SecurityResult<DlpPolicy> {
+        match self.repository.get_policy(tenant_id).await? {
+            Some(record) => to_policy(record),
+            None => Ok(DlpPolicy::disabled(tenant_id)),
+        }
+    }
+
+    pub async fn set_policy(&self, tenant_id: &str, request: SetDlpPolicyRequest) -> SecurityResult<DlpPolicy> {
+        if request.rules.len() > MAX_DLP_RULES {
+            return Err(SecurityError::Validation(format!("A DLP policy has at most {} rules", MAX_DLP_RULES)));
+        }
+        for rule in &request.rules {
+            if rule.id.trim().is_empty() || rule.name.trim().is_empty() {
+                return Err(SecurityError::Validation("DLP rules need an id and a name".to_string()));
+            }
+        }
+
+        // Reject rules the scanning services would fail to compile
+        let candidate = DlpPolicy {
+            tenant_id: tenant_id.to_string(),
+            enabled: request.enabled,
+            rules: request.rules.clone(),
+            updated_by: request.updated_by.clone(),
+            updated_at: None,
+        };
+        if let Err(e) = candidate.compile() {
+            return Err(match e {
+                ServiceError::Validation(message) => SecurityError::Validation(message),
+                other => SecurityError::Validation(other.to_string()),
+            });
+        }
+
+        let record = self
+            .repository
+            .upsert_policy(tenant_id, request.enabled, serde_json::to_value(&request.rules)?, request.updated_by.as_deref())
+            .await?;
+        let policy = to_policy(record)?;
+
+        self.audit_service.log_compliance_event(
+            tenant_id,
+            "dlp_policy",
+            "update_dlp_policy",
+            AuditOutcome::Success,
+            json!({
+                "enabled": policy.enabled,
+                "rules": policy.rules.iter().map(|rule| json!({
+                    "id": rule.id,
+                    "detector": rule.detector.name(),
+                    "action": rule.action,
+                    "enabled": rule.enabled,
+                })).collect::<Vec<_>>(),
+                "updated_by": policy.updated_by,
+            }),
+        ).await?;
+
+        Ok(policy)
+    }
+
+    /// The tenant's DLP incidents, as recorded in its audit log
+    pub async fn list_incidents(
+        &self,
+        tenant_id: &str,
+        mut filter: AuditLogFilter,
+        page: i32,
+        page_size: i32,
+    ) -> SecurityResult<AuditLogResponse> {
+        filter.action = Some(DLP_INCIDENT_ACTION.to_string());
+        self.audit_service.get_audit_logs(tenant_id, &filter, page, page_size).await
+    }
+}
+
+fn to_policy(record: DlpPolicyRecord) -> SecurityResult<DlpPolicy> {
+    Ok(DlpPolicy {
+        tenant_id: record.tenant_id,
+        enabled: record.enabled,
+        rules: serde_json::from_value(record.rules)?,
+        updated_by: record.updated_by,
+        updated_at: Some(record.updated_at),
+    })
+}
@@ -10,10 +10,25 @@ use tokio::sync::RwLock;
 use tracing::{info, warn, error};
 use uuid::Uuid;
 
+/// Starts tenant data encrypted with a managed data key, followed by the key
+/// id's length, the key id, the nonce and the ciphertext. Data without it was
+/// encrypted with the key derived from the master key.
+const KEYED_CIPHERTEXT_MARKER: &[u8; 4] = b"ADXK";
+
+/// A tenant's managed data keys: the one new data is encrypted with, and
+/// every earlier one so data encrypted before a rotation still decrypts
+#[derive(Default)]
+struct TenantKeyRing {
+    active_kid: Option<String>,
+    activated_at: Option<chrono::DateTime<chrono::Utc>>,
+    keys: HashMap<String, Arc<[u8; 32]>>,
+}
+
 #[derive(Clone)]
 pub struct EncryptionService {
     master_key: Arc<[u8; 32]>,
     key_cache: Arc<RwLock<HashMap<String, Arc<[u8; 32]>>>>,
+    tenant_key_rings: Arc<RwLock<HashMap<String, TenantKeyRing>>>,
     algorithm: String,
     key_rotation_days: u32,
 }
@@ -23,6 +38,7 @@ impl EncryptionService {
         Self {
             master_key: Arc::new(master_key),
             key_cache: Arc::new(RwLock::new(HashMap::new())),
+            tenant_key_rings: Arc::new(RwLock::new(HashMap::new())),
             algorithm,
             key_rotation_days,
         }
@@ -59,16 +75,67 @@ impl EncryptionService {
 
     /// Encrypt data with a tenant-specific key
     pub async fn encrypt_tenant_data(&self, tenant_id: &str, data: &[u8]) -> SecurityResult<Vec<u8>> {
+        if let Some((kid, key)) = self.active_tenant_key(tenant_id).await {
+            let ciphertext = self.encrypt_with_key(&key, data).await?;
+
+            let mut result = KEYED_CIPHERTEXT_MARKER.to_vec();
+            result.push(kid.len() as u8);
+            result.extend_from_slice(kid.as_bytes());
+            result.extend_from_slice(&ciphertext);
+            return Ok(result);
+        }
+
         let tenant_key = self.get_tenant_key(tenant_id).await?;
         self.encrypt_with_key(&tenant_key, data).await
     }
 
     /// Decrypt data with a tenant-specific key
     pub async fn decrypt_tenant_data(&self, tenant_id: &str, encrypted_data: &[u8]) -> SecurityResult<Vec<u8>> {
+        if let Some((kid, ciphertext)) = split_keyed_ciphertext(encrypted_data) {
+            let key = self
+                .tenant_key_rings
+                .read()
+                .await
+                .get(tenant_id)
+                .and_then(|ring| ring.keys.get(kid).cloned());
+            if let Some(key) = key {
+                return self.decrypt_with_key(&key, ciphertext).await;
+            }
+        }
+
+        // Legacy data, or a random nonce that happened to look like the marker
         let tenant_key = self.get_tenant_key(tenant_id).await?;
         self.decrypt_with_key(&tenant_key, encrypted_data).await
     }
 
+    /// Replace a tenant's managed data keys. `active` names the key new data
+    /// is encrypted with; the rest stay available for decryption.
+    pub async fn install_tenant_keys(
+        &self,
+        tenant_id: &str,
+        active: Option<(String, chrono::DateTime<chrono::Utc>)>,
+        keys: Vec<(String, [u8; 32])>,
+    ) {
+        let (active_kid, activated_at) = match active {
+            Some((kid, activated_at)) => (Some(kid), Some(activated_at)),
+            None => (None, None),
+        };
+        let ring = TenantKeyRing {
+            active_kid,
+            activated_at,
+            keys: keys.into_iter().map(|(kid, key)| (kid, Arc::new(key))).collect(),
+        };
+
+        self.tenant_key_rings.write().await.insert(tenant_id.to_string(), ring);
+    }
+
+    async fn active_tenant_key(&self, tenant_id: &str) -> Option<(String, Arc<[u8; 32]>)> {
+        let rings = self.tenant_key_rings.read().await;
+        let ring = rings.get(tenant_id)?;
+        let kid = ring.active_kid.as_ref()?;
+        ring.keys.get(kid).map(|key| (kid.clone(), key.clone()))
+    }
+
     /// Hash a password using Argon2
     pub async fn hash_password(&self, password: &str) -> SecurityResult<String> {
         let salt = SaltString::generate(&mut ArgonOsRng);
@@ -144,8 +211,7 @@ impl EncryptionService {
 
     /// Encrypt file data
     pub async fn encrypt_file(&self, file_data: &[u8], tenant_id: &str) -> SecurityResult<EncryptedFile> {
-        let tenant_key = self.get_tenant_key(tenant_id).await?;
-        let encrypted_data = self.encrypt_with_key(&tenant_key, file_data).await?;
+        let encrypted_data = self.encrypt_tenant_data(tenant_id, file_data).await?;
         
         // Generate file-specific metadata
         let file_id = Uuid::new_v4().to_string();
@@ -163,8 +229,9 @@ impl EncryptionService {
 
     /// Decrypt file data
     pub async fn decrypt_file(&self, encrypted_file: &EncryptedFile) -> SecurityResult<Vec<u8>> {
-        let tenant_key = self.get_tenant_key(&encrypted_file.tenant_id).await?;
-        let decrypted_data = self.decrypt_with_key(&tenant_key, &encrypted_file.encrypted_data).await?;
+        let decrypted_data = self
+            .decrypt_tenant_data(&encrypted_file.tenant_id, &encrypted_file.encrypted_data)
+            .await?;
         
         // Verify checksum
         let calculated_checksum = self.calculate_checksum(&decrypted_data);
@@ -193,14 +260,17 @@ impl EncryptionService {
     /// Get encryption status for a tenant
     pub async fn get_encryption_status(&self, tenant_id: &str) -> SecurityResult<EncryptionStatus> {
         let cache = self.key_cache.read().await;
-        let has_key = cache.contains_key(tenant_id);
+        let rings = self.tenant_key_rings.read().await;
+        let ring = rings.get(tenant_id);
+        let has_key = cache.contains_key(tenant_id) || ring.map_or(false, |ring| ring.active_kid.is_some());
         
         Ok(EncryptionStatus {
             tenant_id: tenant_id.to_string(),
             algorithm: self.algorithm.clone(),
             key_exists: has_key,
             key_rotation_days: self.key_rotation_days,
-            last_rotation: None, // This would be tracked in a database
+            active_key_id: ring.and_then(|ring| ring.active_kid.clone()),
+            last_rotation: ring.and_then(|ring| ring.activated_at),
         })
     }
 
@@ -333,6 +403,17 @@ impl EncryptionService {
     }
 }
 
+/// Key id and ciphertext of data encrypted with a managed data key
+fn split_keyed_ciphertext(data: &[u8]) -> Option<(&str, &[u8])> {
+    let rest = data.strip_prefix(KEYED_CIPHERTEXT_MARKER.as_slice())?;
+    let (&kid_len, rest) = rest.split_first()?;
+    if rest.len() < kid_len as usize {
+        return None;
+    }
+    let (kid, ciphertext) = rest.split_at(kid_len as usize);
+    Some((std::str::from_utf8(kid).ok()?, ciphertext))
+}
+
 // Supporting types
 
 #[derive(Debug, Clone, serde::Serialize, serde::Deserialize)]
@@ -351,6 +432,8 @@ pub struct EncryptionStatus {
     pub algorithm: String,
     pub key_exists: bool,
     pub key_rotation_days: u32,
+    /// Managed data key new data is encrypted with
+    pub active_key_id: Option<String>,
     pub last_rotation: Option<chrono::DateTime<chrono::Utc>>,
 }
 
@@ -0,0 +1,627 @@
+use crate::{
+    audit::{AuditService, SERVICE_NAME},
+    config::IncidentConfig,
+    detection::DetectionService,
+    error::{SecurityError, SecurityResult},
+    models::{
+        AcknowledgeAlertRequest, AlertStatus, AuditEventCategory, AuditOutcome, ContainmentAction,
+        ContainmentActionStatus, ContainmentActionType, CreateAuditLogRequest, DecideContainmentRequest,
+        IncidentDetail, IncidentFilter, IncidentNoteRequest, IncidentPostmortem, IncidentStatus,
+        IncidentTimelineEntry, OpenIncidentRequest, RequestContainmentRequest, SecurityAlert,
+        SecurityEventSeverity, SecurityIncident, UpdateIncidentStatusRequest,
+    },
+    repositories::IncidentRepository,
+};
+use chrono::Utc;
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+
+// Incident response. An incident groups the alerts it was opened from and
+// records how it was handled: containment actions against users, modules or
+// the tenant itself, the timeline of everything that happened, and the
+// postmortem. Actions that take a module or tenant offline wait for a second
+// person to approve them; the containment workflow expires them if nobody
+// does in time.
+
+pub const MAX_INCIDENTS_LISTED: i64 = 100;
+
+pub struct IncidentService {
+    repository: Arc<IncidentRepository>,
+    detection_service: Arc<DetectionService>,
+    audit_service: Arc<AuditService>,
+    http_client: reqwest::Client,
+    config: IncidentConfig,
+}
+
+impl IncidentService {
+    pub fn new(
+        repository: Arc<IncidentRepository>,
+        detection_service: Arc<DetectionService>,
+        audit_service: Arc<AuditService>,
+        config: IncidentConfig,
+    ) -> Self {
+        Self {
+            repository,
+            detection_service,
+            audit_service,
+            http_client: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    /// Open an incident from alerts. Open alerts are acknowledged by whoever
+    /// opened the incident; an alert can be part of one active incident.
+    pub async fn open_incident(
+        &self,
+        tenant_id: &str,
+        request: OpenIncidentRequest,
+    ) -> SecurityResult<SecurityIncident> {
+        if request.opened_by.trim().is_empty() {
+            return Err(SecurityError::Validation("opened_by is required".to_string()));
+        }
+        let title = request.title.filter(|title| !title.trim().is_empty());
+        if request.alert_ids.is_empty() && title.is_none() {
+            return Err(SecurityError::Validation(
+                "An incident needs at least one alert or a title".to_string(),
+            ));
+        }
+
+        let mut alerts: Vec<SecurityAlert> = Vec::with_capacity(request.alert_ids.len());
+        for alert_id in &request.alert_ids {
+            if alerts.iter().any(|alert| alert.id == *alert_id) {
+                continue;
+            }
+            if let Some(incident_id) = self.repository.find_active_incident_for_alert(tenant_id, *alert_id).await? {
+                return Err(SecurityError::Conflict(format!(
+                    "Alert {} is already part of incident {}", alert_id, incident_id
+                )));
+            }
+            alerts.push(self.detection_service.get_alert(tenant_id, *alert_id).await?);
+        }
+
+        let severity = request.severity.unwrap_or_else(|| {
+            alerts
+                .iter()
+                .map(|alert| alert.severity.clone())
+                .max_by_key(severity_rank)
+                .unwrap_or(SecurityEventSeverity::Medium)
+        });
+        let now = Utc::now();
+        let incident = SecurityIncident {
+            id: Uuid::new_v4(),
+            tenant_id: tenant_id.to_string(),
+            title: title.unwrap_or_else(|| alerts[0].title.clone()),
+            description: request
+                .description
+                .unwrap_or_else(|| alerts.iter().map(|alert| alert.description.as_str()).collect::<Vec<_>>().join("\n")),
+            severity,
+            status: IncidentStatus::Open,
+            alert_ids: alerts.iter().map(|alert| alert.id).collect(),
+            opened_by: request.opened_by.clone(),
+            assigned_to: request.assigned_to,
+            opened_at: now,
+            contained_at: None,
+            resolved_at: None,
+            closed_at: None,
+            resolution_notes: None,
+            postmortem: None,
+            updated_at: now,
+        };
+        self.repository.create_incident(&incident).await?;
+
+        for alert in alerts.iter().filter(|alert| alert.status == AlertStatus::Open) {
+            let acknowledged = self
+                .detection_service
+                .acknowledge_alert(
+                    tenant_id,
+                    alert.id,
+                    AcknowledgeAlertRequest { acknowledged_by: request.opened_by.clone() },
+                )
+                .await;
+            // Someone else acknowledging it first is fine
+            match acknowledged {
+                Ok(_) | Err(SecurityError::Conflict(_)) => {}
+                Err(e) => return Err(e),
+            }
+        }
+
+        info!(tenant_id = %tenant_id, incident_id = %incident.id, alerts = incident.alert_ids.len(), "Opened security incident");
+        self.record(
+            &incident,
+            "opened",
+            &incident.opened_by,
+            format!("Incident opened: {}", incident.title),
+            json!({ "alert_ids": incident.alert_ids, "severity": incident.severity }),
+        ).await?;
+        self.audit_incident(&incident, "open_incident", &incident.opened_by, AuditOutcome::Success, json!({
+            "alert_ids": incident.alert_ids,
+            "severity": incident.severity,
+            "assigned_to": incident.assigned_to,
+        })).await?;
+        Ok(incident)
+    }
+
+    pub async fn get_incident(&self, tenant_id: &str, incident_id: Uuid) -> SecurityResult<SecurityIncident> {
+        self.repository
+            .get_incident(tenant_id, incident_id)
+            .await?
+            .ok_or_else(|| SecurityError::NotFound(format!("Incident {} not found", incident_id)))
+    }
+
+    pub async fn get_detail(&self, tenant_id: &str, incident_id: Uuid) -> SecurityResult<IncidentDetail> {
+        let incident = self.get_incident(tenant_id, incident_id).await?;
+        let actions = self.repository.list_actions(incident_id).await?;
+        let timeline = self.repository.list_timeline(incident_id).await?;
+        Ok(IncidentDetail { incident, actions, timeline })
+    }
+
+    pub async fn list_incidents(
+        &self,
+        tenant_id: &str,
+        filter: &IncidentFilter,
+    ) -> SecurityResult<Vec<SecurityIncident>> {
+        self.repository.list_incidents(tenant_id, filter, MAX_INCIDENTS_LISTED).await
+    }
+
+    pub async fn add_note(
+        &self,
+        tenant_id: &str,
+        incident_id: Uuid,
+        request: IncidentNoteRequest,
+    ) -> SecurityResult<IncidentTimelineEntry> {
+        if request.message.trim().is_empty() {
+            return Err(SecurityError::Validation("message is required".to_string()));
+        }
+        let incident = self.get_incident(tenant_id, incident_id).await?;
+        self.record(&incident, "note", &request.author, request.message, json!({})).await
+    }
+
+    /// Request a containment action. Actions that need no approval run
+    /// straight away; the rest wait for someone other than the requester.
+    pub async fn request_action(
+        &self,
+        tenant_id: &str,
+        incident_id: Uuid,
+        request: RequestContainmentRequest,
+    ) -> SecurityResult<ContainmentAction> {
+        if request.requested_by.trim().is_empty() {
+            return Err(SecurityError::Validation("requested_by is required".to_string()));
+        }
+        if request.target.trim().is_empty() {
+            return Err(SecurityError::Validation("target is required".to_string()));
+        }
+        if request.action_type == ContainmentActionType::LockTenant && request.target != tenant_id {
+            return Err(SecurityError::Authorization(
+                "An incident can only lock its own tenant".to_string(),
+            ));
+        }
+
+        let incident = self.get_incident(tenant_id, incident_id).await?;
+        if !matches!(incident.status, IncidentStatus::Open | IncidentStatus::Contained) {
+            return Err(SecurityError::Conflict(
+                "Containment actions can only be taken on active incidents".to_string(),
+            ));
+        }
+
+        let requires_approval = request.action_type.requires_approval();
+        let action = ContainmentAction {
+            id: Uuid::new_v4(),
+            incident_id,
+            tenant_id: tenant_id.to_string(),
+            action_type: request.action_type,
+            target: request.target,
+            reason: request.reason,
+            requires_approval,
+            status: if requires_approval {
+                ContainmentActionStatus::PendingApproval
+            } else {
+                ContainmentActionStatus::Approved
+            },
+            requested_by: request.requested_by,
+            requested_at: Utc::now(),
+            decided_by: None,
+            decided_at: None,
+            decision_comment: None,
+            executed_at: None,
+            result: None,
+            error: None,
+        };
+        self.repository.create_action(&action).await?;
+
+        self.record(
+            &incident,
+            "action_requested",
+            &action.requested_by,
+            format!("Requested {} on {}", action.action_type.as_str(), action.target),
+            json!({ "action_id": action.id, "requires_approval": requires_approval, "reason": action.reason }),
+        ).await?;
+        self.audit_action(&action, "request_containment_action", &action.requested_by, AuditOutcome::Success).await?;
+
+        if requires_approval {
+            return Ok(action);
+        }
+        self.execute_action(tenant_id, incident_id, action.id).await
+    }
+
+    pub async fn get_action(
+        &self,
+        tenant_id: &str,
+        incident_id: Uuid,
+        action_id: Uuid,
+    ) -> SecurityResult<ContainmentAction> {
+        self.repository
+            .get_action(incident_id, action_id)
+            .await?
+            .filter(|action| action.tenant_id == tenant_id)
+            .ok_or_else(|| SecurityError::NotFound(format!("Containment action {} not found", action_id)))
+    }
+
+    /// Approve an action and run it. The requester cannot approve their own action.
+    pub async fn approve_action(
+        &self,
+        tenant_id: &str,
+        incident_id: Uuid,
+        action_id: Uuid,
+        request: DecideContainmentRequest,
+    ) -> SecurityResult<ContainmentAction> {
+        let action = self.get_action(tenant_id, incident_id, action_id).await?;
+        if action.requested_by == request.decided_by {
+            return Err(SecurityError::Authorization(
+                "A containment action must be approved by someone other than its requester".to_string(),
+            ));
+        }
+        self.decide(action, ContainmentActionStatus::Approved, &request.decided_by, request.comment.as_deref()).await?;
+        self.execute_action(tenant_id, incident_id, action_id).await
+    }
+
+    pub async fn reject_action(
+        &self,
+        tenant_id: &str,
+        incident_id: Uuid,
+        action_id: Uuid,
+        request: DecideContainmentRequest,
+    ) -> SecurityResult<ContainmentAction> {
+        let action = self.get_action(tenant_id, incident_id, action_id).await?;
+        self.decide(action, ContainmentActionStatus::Rejected, &request.decided_by, request.comment.as_deref()).await
+    }
+
+    /// Expire an action nobody approved in time. Actions already decided are
+    /// returned unchanged.
+    pub async fn expire_action(
+        &self,
+        tenant_id: &str,
+        incident_id: Uuid,
+        action_id: Uuid,
+    ) -> SecurityResult<ContainmentAction> {
+        let action = self.get_action(tenant_id, incident_id, action_id).await?;
+        if action.status != ContainmentActionStatus::PendingApproval {
+            return Ok(action);
+        }
+        self.decide(action, ContainmentActionStatus::Expired, SERVICE_NAME, Some("Approval timed out")).await
+    }
+
+    /// Run an approved action against the service that owns its target. A
+    /// failed action is recorded as failed rather than returned as an error.
+    pub async fn execute_action(
+        &self,
+        tenant_id: &str,
+        incident_id: Uuid,
+        action_id: Uuid,
+    ) -> SecurityResult<ContainmentAction> {
+        let incident = self.get_incident(tenant_id, incident_id).await?;
+        let action = match self.repository.claim_action(action_id).await? {
+            Some(action) => action,
+            None => {
+                let action = self.get_action(tenant_id, incident_id, action_id).await?;
+                if action.status == ContainmentActionStatus::PendingApproval {
+                    return Err(SecurityError::Conflict("Containment action has not been approved".to_string()));
+                }
+                // Already run, running or decided against
+                return Ok(action);
+            }
+        };
+        let actor = action.decided_by.clone().unwrap_or_else(|| action.requested_by.clone());
+
+        let action = match self.perform(&action, &actor).await {
+            Ok(result) => {
+                info!(tenant_id = %tenant_id, incident_id = %incident_id, action = action.action_type.as_str(), target = %action.target, "Containment action completed");
+                self.repository
+                    .finish_action(action.id, ContainmentActionStatus::Completed, Some(&result), None)
+                    .await?
+            }
+            Err(e) => {
+                error!(tenant_id = %tenant_id, incident_id = %incident_id, action = action.action_type.as_str(), target = %action.target, error = %e, "Containment action failed");
+                self.repository
+                    .finish_action(action.id, ContainmentActionStatus::Failed, None, Some(&e.to_string()))
+                    .await?
+            }
+        };
+
+        let completed = action.status == ContainmentActionStatus::Completed;
+        self.record(
+            &incident,
+            if completed { "action_completed" } else { "action_failed" },
+            &actor,
+            format!(
+                "{} on {} {}",
+                action.action_type.as_str(),
+                action.target,
+                if completed { "completed" } else { "failed" }
+            ),
+            json!({ "action_id": action.id, "result": action.result, "error": action.error }),
+        ).await?;
+        self.audit_action(
+            &action,
+            "execute_containment_action",
+            &actor,
+            if completed { AuditOutcome::Success } else { AuditOutcome::Failure },
+        ).await?;
+        Ok(action)
+    }
+
+    /// Move an incident forward. High and critical incidents cannot be closed
+    /// without a postmortem.
+    pub async fn update_status(
+        &self,
+        tenant_id: &str,
+        incident_id: Uuid,
+        request: UpdateIncidentStatusRequest,
+    ) -> SecurityResult<SecurityIncident> {
+        let mut incident = self.get_incident(tenant_id, incident_id).await?;
+        let allowed = matches!(
+            (incident.status, request.status),
+            (IncidentStatus::Open, IncidentStatus::Contained)
+                | (IncidentStatus::Open, IncidentStatus::Resolved)
+                | (IncidentStatus::Contained, IncidentStatus::Resolved)
+                | (IncidentStatus::Resolved, IncidentStatus::Closed)
+        );
+        if !allowed {
+            return Err(SecurityError::Conflict(format!(
+                "Incident cannot move from {:?} to {:?}", incident.status, request.status
+            )));
+        }
+        if request.status == IncidentStatus::Closed && severity_rank(&incident.severity) >= 3 && incident.postmortem.is_none() {
+            return Err(SecurityError::Validation(
+                "High and critical incidents need a postmortem before they are closed".to_string(),
+            ));
+        }
+
+        let now = Utc::now();
+        let previous = incident.status;
+        incident.status = request.status;
+        match request.status {
+            IncidentStatus::Contained => incident.contained_at = Some(now),
+            IncidentStatus::Resolved => {
+                incident.resolved_at = Some(now);
+                incident.resolution_notes = request.notes.clone();
+            }
+            IncidentStatus::Closed => incident.closed_at = Some(now),
+            IncidentStatus::Open => {}
+        }
+        incident.updated_at = now;
+        let incident = self.repository.update_incident(incident).await?;
+
+        info!(tenant_id = %tenant_id, incident_id = %incident_id, status = ?incident.status, "Security incident status changed");
+        self.record(
+            &incident,
+            "status_changed",
+            &request.updated_by,
+            format!("Status changed from {:?} to {:?}", previous, incident.status),
+            json!({ "from": previous, "to": incident.status, "notes": request.notes }),
+        ).await?;
+        self.audit_incident(&incident, "update_incident_status", &request.updated_by, AuditOutcome::Success, json!({
+            "from": previous,
+            "to": incident.status,
+        })).await?;
+        Ok(incident)
+    }
+
+    pub async fn set_postmortem(
+        &self,
+        tenant_id: &str,
+        incident_id: Uuid,
+        mut postmortem: IncidentPostmortem,
+    ) -> SecurityResult<SecurityIncident> {
+        if postmortem.author.trim().is_empty() {
+            return Err(SecurityError::Validation("author is required".to_string()));
+        }
+        if postmortem.summary.trim().is_empty() || postmortem.root_cause.trim().is_empty() {
+            return Err(SecurityError::Validation("A postmortem needs a summary and a root cause".to_string()));
+        }
+        let mut incident = self.get_incident(tenant_id, incident_id).await?;
+        if matches!(incident.status, IncidentStatus::Open | IncidentStatus::Contained) {
+            return Err(SecurityError::Conflict("Postmortems are written once the incident is resolved".to_string()));
+        }
+
+        let now = Utc::now();
+        postmortem.updated_at = Some(now);
+        let author = postmortem.author.clone();
+        incident.postmortem = Some(serde_json::to_value(&postmortem)?);
+        incident.updated_at = now;
+        let incident = self.repository.update_incident(incident).await?;
+
+        self.record(&incident, "postmortem_updated", &author, "Postmortem updated".to_string(), json!({})).await?;
+        self.audit_incident(&incident, "set_incident_postmortem", &author, AuditOutcome::Success, json!({})).await?;
+        Ok(incident)
+    }
+
+    async fn decide(
+        &self,
+        action: ContainmentAction,
+        status: ContainmentActionStatus,
+        decided_by: &str,
+        comment: Option<&str>,
+    ) -> SecurityResult<ContainmentAction> {
+        if decided_by.trim().is_empty() {
+            return Err(SecurityError::Validation("decided_by is required".to_string()));
+        }
+        let decided = self
+            .repository
+            .decide_action(action.id, status, decided_by, comment)
+            .await?
+            .ok_or_else(|| SecurityError::Conflict(format!(
+                "Containment action is {:?}, not awaiting approval", action.status
+            )))?;
+
+        if status == ContainmentActionStatus::Expired {
+            warn!(tenant_id = %decided.tenant_id, incident_id = %decided.incident_id, action_id = %decided.id, "Containment action expired without approval");
+        }
+        let incident = self.get_incident(&decided.tenant_id, decided.incident_id).await?;
+        let verb = match status {
+            ContainmentActionStatus::Approved => "approved",
+            ContainmentActionStatus::Rejected => "rejected",
+            _ => "expired",
+        };
+        self.record(
+            &incident,
+            &format!("action_{}", verb),
+            decided_by,
+            format!("{} on {} {}", decided.action_type.as_str(), decided.target, verb),
+            json!({ "action_id": decided.id, "comment": comment }),
+        ).await?;
+        self.audit_action(&decided, &format!("{}_containment_action", verb), decided_by, AuditOutcome::Success).await?;
+        Ok(decided)
+    }
+
+    async fn perform(&self, action: &ContainmentAction, actor: &str) -> SecurityResult<Value> {
+        let request = match action.action_type {
+            ContainmentActionType::RevokeSessions => self.http_client.delete(format!(
+                "{}/api/v1/users/{}/sessions",
+                self.config.auth_service_url.trim_end_matches('/'),
+                action.target
+            )),
+            ContainmentActionType::DisableApiKeys => self.http_client.delete(format!(
+                "{}/api/v1/users/{}/api-keys",
+                self.config.auth_service_url.trim_end_matches('/'),
+                action.target
+            )),
+            ContainmentActionType::QuarantineModule => self.http_client.post(format!(
+                "{}/api/v1/modules/{}/deactivate",
+                self.config.module_service_url.trim_end_matches('/'),
+                action.target
+            )),
+            ContainmentActionType::LockTenant => self
+                .http_client
+                .put(format!(
+                    "{}/api/v1/tenants/{}",
+                    self.config.tenant_service_url.trim_end_matches('/'),
+                    action.target
+                ))
+                .json(&json!({ "status": "Suspended" })),
+        };
+
+        let response = request
+            .header("X-Tenant-ID", &action.tenant_id)
+            .header("X-User-ID", actor)
+            .send()
+            .await?;
+        let status = response.status();
+        if !status.is_success() {
+            return Err(SecurityError::ServiceUnavailable(format!(
+                "{} on {} returned {}", action.action_type.as_str(), action.target, status
+            )));
+        }
+        let body = response.json::<Value>().await.unwrap_or(Value::Null);
+        Ok(json!({ "status": status.as_u16(), "response": body }))
+    }
+
+    async fn record(
+        &self,
+        incident: &SecurityIncident,
+        entry_type: &str,
+        actor: &str,
+        message: String,
+        details: Value,
+    ) -> SecurityResult<IncidentTimelineEntry> {
+        let entry = IncidentTimelineEntry {
+            id: Uuid::new_v4(),
+            incident_id: incident.id,
+            tenant_id: incident.tenant_id.clone(),
+            entry_type: entry_type.to_string(),
+            actor: actor.to_string(),
+            message,
+            details,
+            occurred_at: Utc::now(),
+        };
+        self.repository.add_timeline_entry(&entry).await?;
+        Ok(entry)
+    }
+
+    async fn audit_action(
+        &self,
+        action: &ContainmentAction,
+        audit_action: &str,
+        actor: &str,
+        outcome: AuditOutcome,
+    ) -> SecurityResult<Uuid> {
+        self.audit_event(
+            &action.tenant_id,
+            "containment_action",
+            action.id,
+            audit_action,
+            actor,
+            outcome,
+            json!({
+                "incident_id": action.incident_id,
+                "action_type": action.action_type,
+                "target": action.target,
+                "status": action.status,
+                "error": action.error,
+            }),
+        ).await
+    }
+
+    async fn audit_incident(
+        &self,
+        incident: &SecurityIncident,
+        action: &str,
+        actor: &str,
+        outcome: AuditOutcome,
+        details: Value,
+    ) -> SecurityResult<Uuid> {
+        self.audit_event(&incident.tenant_id, "security_incident", incident.id, action, actor, outcome, details).await
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn audit_event(
+        &self,
+        tenant_id: &str,
+        resource_type: &str,
+        resource_id: Uuid,
+        action: &str,
+        actor: &str,
+        outcome: AuditOutcome,
+        details: Value,
+    ) -> SecurityResult<Uuid> {
+        self.audit_service.log_event(CreateAuditLogRequest {
+            event_id: None,
+            tenant_id: tenant_id.to_string(),
+            service: SERVICE_NAME.to_string(),
+            user_id: Some(actor.to_string()),
+            session_id: None,
+            event_type: "security_incident".to_string(),
+            event_category: AuditEventCategory::Security,
+            resource_type: resource_type.to_string(),
+            resource_id: Some(resource_id.to_string()),
+            action: action.to_string(),
+            outcome,
+            ip_address: None,
+            user_agent: None,
+            request_id: None,
+            details,
+            occurred_at: None,
+        }).await
+    }
+}
+
+fn severity_rank(severity: &SecurityEventSeverity) -> u8 {
+    match severity {
+        SecurityEventSeverity::Info => 0,
+        SecurityEventSeverity::Low => 1,
+        SecurityEventSeverity::Medium => 2,
+        SecurityEventSeverity::High => 3,
+        SecurityEventSeverity::Critical => 4,
+    }
+}
@@ -0,0 +1,468 @@
+use crate::{
+    audit::{AuditService, SERVICE_NAME},
+    config::KeyRotationConfig,
+    encryption::EncryptionService,
+    error::{SecurityError, SecurityResult},
+    models::{
+        AuditEventCategory, AuditOutcome, CreateAuditLogRequest, KeyLifecycle, KeyMaintenanceSummary,
+        KeyPurpose, KeyRotationSchedule, KeyStatus, ManagedKey, SetKeyRotationScheduleRequest,
+    },
+    repositories::KeyRepository,
+};
+use adx_shared::signing_keys::{SigningKey, SigningKeySet, SigningKeyStatus, PLATFORM_KEY_OWNER};
+use chrono::{DateTime, Duration, Utc};
+use serde_json::json;
+use std::{collections::HashMap, sync::Arc};
+use tracing::{error, info};
+use uuid::Uuid;
+
+// Key lifecycle. A rotation takes three steps so nothing signed or encrypted
+// with a key stops working while it happens:
+//   1. stage: a pending key is published to validators
+//   2. activate: once validators have had time to fetch it, the new key signs
+//      and encrypts, and the key it replaces starts retiring
+//   3. retire: when the overlap window closes the old key is no longer accepted
+// Retired data keys are kept; they still decrypt what they encrypted.
+
+pub const MAX_ROTATION_DAYS: i32 = 365;
+pub const MAX_OVERLAP_HOURS: i32 = 24 * 14;
+
+/// Who staged keys the rotation schedules asked for
+const SCHEDULER: &str = "key-rotation-scheduler";
+
+pub struct KeyManagementService {
+    repository: Arc<KeyRepository>,
+    encryption: Arc<EncryptionService>,
+    audit_service: Arc<AuditService>,
+    http_client: reqwest::Client,
+    config: KeyRotationConfig,
+}
+
+impl KeyManagementService {
+    pub fn new(
+        repository: Arc<KeyRepository>,
+        encryption: Arc<EncryptionService>,
+        audit_service: Arc<AuditService>,
+        config: KeyRotationConfig,
+    ) -> Self {
+        Self {
+            repository,
+            encryption,
+            audit_service,
+            http_client: reqwest::Client::new(),
+            config,
+        }
+    }
+
+    pub fn default_overlap_hours(&self, purpose: KeyPurpose) -> i32 {
+        match purpose {
+            KeyPurpose::JwtSigning => self.config.jwt_overlap_hours,
+            KeyPurpose::TenantData => self.config.data_key_overlap_hours,
+            KeyPurpose::WebhookSigning => self.config.webhook_overlap_hours,
+        }
+    }
+
+    /// Stage a new key for validators to pick up. It becomes active once it
+    /// has been published for the propagation period; staging again while a
+    /// key is pending returns that key.
+    pub async fn stage_key(&self, purpose: KeyPurpose, owner_id: &str, rotated_by: &str) -> SecurityResult<ManagedKey> {
+        validate_owner(purpose, owner_id)?;
+
+        if let Some(pending) = self.repository.find_key(purpose, owner_id, KeyStatus::Pending).await? {
+            return Ok(pending);
+        }
+
+        let material = self.generate_material(purpose).await?;
+        let key = self
+            .repository
+            .create_key(ManagedKey {
+                id: Uuid::new_v4(),
+                purpose,
+                owner_id: owner_id.to_string(),
+                kid: new_kid(purpose),
+                status: KeyStatus::Pending,
+                material: self.encryption.encrypt_data(&material).await?,
+                rotated_by: Some(rotated_by.to_string()),
+                created_at: Utc::now(),
+                activated_at: None,
+                retiring_at: None,
+                retire_after: None,
+                retired_at: None,
+            })
+            .await?;
+
+        if purpose == KeyPurpose::TenantData {
+            self.reload_tenant_keys(owner_id).await?;
+        }
+
+        info!(purpose = purpose.as_str(), owner_id = %owner_id, kid = %key.kid, "Staged key");
+        self.log_key_event(&key, "key_staged", json!({ "rotated_by": rotated_by })).await;
+        Ok(key)
+    }
+
+    /// Make a staged key the active one. The key it replaces stays valid for
+    /// `overlap_hours`.
+    pub async fn activate_key(&self, key: &ManagedKey, overlap_hours: i32) -> SecurityResult<ManagedKey> {
+        if key.status == KeyStatus::Active {
+            return Ok(key.clone());
+        }
+
+        // Tenant-service must sign with the new secret before it is reported active here
+        if key.purpose == KeyPurpose::WebhookSigning {
+            let secret = self.unwrap_secret(key).await?;
+            self.push_webhook_secret(&key.owner_id, &secret, overlap_hours).await?;
+        }
+
+        let retire_after = Utc::now() + Duration::hours(overlap_hours as i64);
+        let activated = self.repository.activate_key(key.id, retire_after).await?;
+
+        if key.purpose == KeyPurpose::TenantData {
+            self.reload_tenant_keys(&key.owner_id).await?;
+        }
+
+        info!(
+            purpose = key.purpose.as_str(),
+            owner_id = %key.owner_id,
+            kid = %key.kid,
+            retire_after = %retire_after,
+            "Activated key"
+        );
+        self.log_key_event(&activated, "key_activated", json!({ "previous_key_valid_until": retire_after }))
+            .await;
+        Ok(activated)
+    }
+
+    /// Activate a staged key by id, with the owner's configured overlap unless
+    /// one is given. Returns when the key it replaced stops being accepted.
+    pub async fn activate(&self, kid: &str, overlap_hours: Option<i32>) -> SecurityResult<DateTime<Utc>> {
+        let key = self
+            .repository
+            .get_key(kid)
+            .await?
+            .ok_or_else(|| SecurityError::NotFound(format!("Key {} not found", kid)))?;
+
+        let overlap_hours = match overlap_hours {
+            Some(hours) => hours,
+            None => self.overlap_hours(key.purpose, &key.owner_id).await?,
+        };
+        let key = self.activate_key(&key, overlap_hours).await?;
+        Ok(key.activated_at.unwrap_or_else(Utc::now) + Duration::hours(overlap_hours as i64))
+    }
+
+    /// Retire the owner's keys whose overlap window has closed
+    pub async fn retire_expired(&self, purpose: KeyPurpose, owner_id: &str) -> SecurityResult<usize> {
+        let mut retired = 0;
+        for key in self.repository.list_expired_keys(Utc::now()).await? {
+            if key.purpose == purpose && key.owner_id == owner_id {
+                self.retire_key(&key).await?;
+                retired += 1;
+            }
+        }
+        Ok(retired)
+    }
+
+    /// Stop accepting a key whose overlap window has closed
+    pub async fn retire_key(&self, key: &ManagedKey) -> SecurityResult<()> {
+        self.repository.retire_key(key.id).await?;
+
+        if key.purpose == KeyPurpose::TenantData {
+            self.reload_tenant_keys(&key.owner_id).await?;
+        }
+
+        info!(purpose = key.purpose.as_str(), owner_id = %key.owner_id, kid = %key.kid, "Retired key");
+        self.log_key_event(key, "key_retired", json!({})).await;
+        Ok(())
+    }
+
+    /// Start a rotation outside the schedule
+    pub async fn rotate(&self, purpose: KeyPurpose, owner_id: &str, requested_by: &str) -> SecurityResult<ManagedKey> {
+        if requested_by.is_empty() {
+            return Err(SecurityError::Validation("requested_by is required".to_string()));
+        }
+        self.stage_key(purpose, owner_id, requested_by).await
+    }
+
+    /// Move every key along its lifecycle: stage keys whose schedule is due,
+    /// activate keys that have propagated and retire keys whose overlap
+    /// window has closed. One key failing does not hold up the rest.
+    pub async fn run_due(&self) -> SecurityResult<KeyMaintenanceSummary> {
+        let now = Utc::now();
+        let mut summary = KeyMaintenanceSummary::default();
+
+        for schedule in self.repository.list_due_schedules(now).await? {
+            match self.stage_key(schedule.purpose, &schedule.owner_id, SCHEDULER).await {
+                Ok(_) => {
+                    let next_rotation_at = now + Duration::days(schedule.rotation_days as i64);
+                    self.repository
+                        .mark_schedule_rotated(schedule.purpose, &schedule.owner_id, now, next_rotation_at)
+                        .await?;
+                    summary.staged += 1;
+                }
+                Err(e) => {
+                    error!(purpose = schedule.purpose.as_str(), owner_id = %schedule.owner_id, error = %e, "Failed to stage scheduled key");
+                    summary.failed += 1;
+                }
+            }
+        }
+
+        let propagated_before = now - Duration::minutes(self.config.propagation_minutes);
+        for key in self.repository.list_pending_keys(propagated_before).await? {
+            let overlap_hours = self.overlap_hours(key.purpose, &key.owner_id).await?;
+            match self.activate_key(&key, overlap_hours).await {
+                Ok(_) => summary.activated += 1,
+                Err(e) => {
+                    error!(kid = %key.kid, error = %e, "Failed to activate key");
+                    summary.failed += 1;
+                }
+            }
+        }
+
+        for key in self.repository.list_expired_keys(now).await? {
+            match self.retire_key(&key).await {
+                Ok(()) => summary.retired += 1,
+                Err(e) => {
+                    error!(kid = %key.kid, error = %e, "Failed to retire key");
+                    summary.failed += 1;
+                }
+            }
+        }
+
+        // Pick up data keys other instances rotated
+        self.load_tenant_keys().await?;
+
+        Ok(summary)
+    }
+
+    /// Keys validators accept for a signing purpose, with their secrets
+    pub async fn key_set(&self, purpose: KeyPurpose, owner_id: &str) -> SecurityResult<SigningKeySet> {
+        if !purpose.is_published() {
+            return Err(SecurityError::Validation(format!("{} keys are not published", purpose.as_str())));
+        }
+
+        let mut keys = Vec::new();
+        for key in self.repository.list_keys(purpose, owner_id).await? {
+            let status = match key.status {
+                KeyStatus::Pending => SigningKeyStatus::Pending,
+                KeyStatus::Active => SigningKeyStatus::Active,
+                KeyStatus::Retiring => SigningKeyStatus::Retiring,
+                KeyStatus::Retired => continue,
+            };
+            keys.push(SigningKey {
+                secret: self.unwrap_secret(&key).await?,
+                kid: key.kid,
+                status,
+                activated_at: key.activated_at,
+                expires_at: key.retire_after.filter(|_| status == SigningKeyStatus::Retiring),
+            });
+        }
+
+        Ok(SigningKeySet {
+            purpose: purpose.as_str().to_string(),
+            owner_id: owner_id.to_string(),
+            keys,
+            generated_at: Utc::now(),
+        })
+    }
+
+    pub async fn lifecycle(&self, purpose: KeyPurpose, owner_id: &str) -> SecurityResult<KeyLifecycle> {
+        Ok(KeyLifecycle {
+            purpose,
+            owner_id: owner_id.to_string(),
+            keys: self.repository.list_keys(purpose, owner_id).await?,
+            schedule: self.repository.get_schedule(purpose, owner_id).await?,
+        })
+    }
+
+    pub async fn list_schedules(&self) -> SecurityResult<Vec<KeyRotationSchedule>> {
+        self.repository.list_schedules().await
+    }
+
+    /// Rotate a key every `rotation_days`. A key without an active version
+    /// gets its first one on the next pass.
+    pub async fn set_schedule(
+        &self,
+        purpose: KeyPurpose,
+        owner_id: &str,
+        request: SetKeyRotationScheduleRequest,
+    ) -> SecurityResult<KeyRotationSchedule> {
+        validate_owner(purpose, owner_id)?;
+        if !(1..=MAX_ROTATION_DAYS).contains(&request.rotation_days) {
+            return Err(SecurityError::Validation(format!(
+                "rotation_days must be between 1 and {}",
+                MAX_ROTATION_DAYS
+            )));
+        }
+        let overlap_hours = request.overlap_hours.unwrap_or_else(|| self.default_overlap_hours(purpose));
+        if !(1..=MAX_OVERLAP_HOURS).contains(&overlap_hours) {
+            return Err(SecurityError::Validation(format!(
+                "overlap_hours must be between 1 and {}",
+                MAX_OVERLAP_HOURS
+            )));
+        }
+
+        let now = Utc::now();
+        let existing = self.repository.get_schedule(purpose, owner_id).await?;
+        let has_active = self.repository.find_key(purpose, owner_id, KeyStatus::Active).await?.is_some();
+        let last_rotated_at = existing.as_ref().and_then(|schedule| schedule.last_rotated_at);
+        let next_rotation_at = match last_rotated_at {
+            _ if !has_active => now,
+            Some(last_rotated_at) => last_rotated_at + Duration::days(request.rotation_days as i64),
+            None => now + Duration::days(request.rotation_days as i64),
+        };
+
+        self.repository
+            .upsert_schedule(KeyRotationSchedule {
+                purpose,
+                owner_id: owner_id.to_string(),
+                rotation_days: request.rotation_days,
+                overlap_hours,
+                enabled: request.enabled.unwrap_or(true),
+                last_rotated_at,
+                next_rotation_at,
+                created_at: existing.as_ref().map_or(now, |schedule| schedule.created_at),
+                updated_at: now,
+            })
+            .await
+    }
+
+    /// Hand every tenant's data keys to the encryption service
+    pub async fn load_tenant_keys(&self) -> SecurityResult<usize> {
+        let mut by_tenant: HashMap<String, Vec<ManagedKey>> = HashMap::new();
+        for key in self.repository.list_keys_by_purpose(KeyPurpose::TenantData, None).await? {
+            by_tenant.entry(key.owner_id.clone()).or_default().push(key);
+        }
+
+        let tenants = by_tenant.len();
+        for (tenant_id, keys) in by_tenant {
+            self.install_tenant_keys(&tenant_id, keys).await?;
+        }
+        Ok(tenants)
+    }
+
+    async fn reload_tenant_keys(&self, tenant_id: &str) -> SecurityResult<()> {
+        let keys = self.repository.list_keys(KeyPurpose::TenantData, tenant_id).await?;
+        self.install_tenant_keys(tenant_id, keys).await
+    }
+
+    async fn install_tenant_keys(&self, tenant_id: &str, keys: Vec<ManagedKey>) -> SecurityResult<()> {
+        let active = keys
+            .iter()
+            .find(|key| key.status == KeyStatus::Active)
+            .map(|key| (key.kid.clone(), key.activated_at.unwrap_or(key.created_at)));
+
+        let mut material = Vec::with_capacity(keys.len());
+        for key in &keys {
+            let bytes = self.encryption.decrypt_data(&key.material).await?;
+            let data_key: [u8; 32] = bytes
+                .try_into()
+                .map_err(|_| SecurityError::Encryption(format!("Data key {} is not 256 bits", key.kid)))?;
+            material.push((key.kid.clone(), data_key));
+        }
+
+        self.encryption.install_tenant_keys(tenant_id, active, material).await;
+        Ok(())
+    }
+
+    async fn overlap_hours(&self, purpose: KeyPurpose, owner_id: &str) -> SecurityResult<i32> {
+        Ok(self
+            .repository
+            .get_schedule(purpose, owner_id)
+            .await?
+            .map_or_else(|| self.default_overlap_hours(purpose), |schedule| schedule.overlap_hours))
+    }
+
+    async fn generate_material(&self, purpose: KeyPurpose) -> SecurityResult<Vec<u8>> {
+        match purpose {
+            KeyPurpose::TenantData => Ok(self.encryption.generate_key().await?.to_vec()),
+            // Signing secrets are handed out as text
+            KeyPurpose::JwtSigning | KeyPurpose::WebhookSigning => {
+                Ok(self.encryption.generate_token(32).await?.into_bytes())
+            }
+        }
+    }
+
+    async fn unwrap_secret(&self, key: &ManagedKey) -> SecurityResult<String> {
+        let bytes = self.encryption.decrypt_data(&key.material).await?;
+        String::from_utf8(bytes).map_err(|_| SecurityError::Encryption(format!("Key {} is not a text secret", key.kid)))
+    }
+
+    async fn push_webhook_secret(&self, webhook_id: &str, secret: &str, overlap_hours: i32) -> SecurityResult<()> {
+        let url = format!(
+            "{}/api/v1/admin/tenant-webhooks/{}/rotate-secret",
+            self.config.tenant_service_url.trim_end_matches('/'),
+            webhook_id
+        );
+
+        let response = self
+            .http_client
+            .post(&url)
+            .json(&json!({ "secret": secret, "overlap_hours": overlap_hours }))
+            .send()
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(SecurityError::ServiceUnavailable(format!(
+                "Tenant service rejected the secret for webhook {}: {}",
+                webhook_id,
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+
+    async fn log_key_event(&self, key: &ManagedKey, action: &str, details: serde_json::Value) {
+        let tenant_id = match key.purpose {
+            KeyPurpose::TenantData => key.owner_id.clone(),
+            KeyPurpose::JwtSigning | KeyPurpose::WebhookSigning => PLATFORM_KEY_OWNER.to_string(),
+        };
+
+        let result = self
+            .audit_service
+            .log_event(CreateAuditLogRequest {
+                event_id: None,
+                tenant_id,
+                service: SERVICE_NAME.to_string(),
+                user_id: key.rotated_by.clone(),
+                session_id: None,
+                event_type: "key_lifecycle".to_string(),
+                event_category: AuditEventCategory::Security,
+                resource_type: key.purpose.as_str().to_string(),
+                resource_id: Some(key.kid.clone()),
+                action: action.to_string(),
+                outcome: AuditOutcome::Success,
+                ip_address: None,
+                user_agent: None,
+                request_id: None,
+                details: json!({ "owner_id": key.owner_id, "details": details }),
+                occurred_at: None,
+            })
+            .await;
+
+        if let Err(e) = result {
+            error!(kid = %key.kid, error = %e, "Failed to audit key lifecycle event");
+        }
+    }
+}
+
+/// JWT signing keys belong to the platform; other keys to a tenant or webhook
+fn validate_owner(purpose: KeyPurpose, owner_id: &str) -> SecurityResult<()> {
+    match purpose {
+        KeyPurpose::JwtSigning if owner_id != PLATFORM_KEY_OWNER => Err(SecurityError::Validation(format!(
+            "JWT signing keys belong to '{}'",
+            PLATFORM_KEY_OWNER
+        ))),
+        KeyPurpose::TenantData | KeyPurpose::WebhookSigning if owner_id.is_empty() || owner_id == PLATFORM_KEY_OWNER => {
+            Err(SecurityError::Validation(format!("{} keys need a tenant or webhook owner", purpose.as_str())))
+        }
+        _ => Ok(()),
+    }
+}
+
+fn new_kid(purpose: KeyPurpose) -> String {
+    let prefix = match purpose {
+        KeyPurpose::JwtSigning => "jwt",
+        KeyPurpose::TenantData => "data",
+        KeyPurpose::WebhookSigning => "whsec",
+    };
+    format!("{}-{}-{}", prefix, Utc::now().format("%Y%m%d"), &Uuid::new_v4().simple().to_string()[..12])
+}
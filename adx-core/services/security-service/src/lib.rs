@@ -1,11 +1,18 @@
+pub mod access_reviews;
 pub mod activities;
 pub mod audit;
 pub mod compliance;
 pub mod config;
+pub mod destruction;
+pub mod detection;
+pub mod dlp;
 pub mod encryption;
 pub mod error;
 pub mod gdpr;
+pub mod incidents;
+pub mod keys;
 pub mod models;
+pub mod network;
 pub mod repositories;
 pub mod retention;
 pub mod scanning;
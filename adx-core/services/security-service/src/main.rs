@@ -6,7 +6,7 @@ use security_service::{
     worker::SecurityWorker,
 };
 use tracing::{info, error};
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use adx_shared::telemetry::init_tracing;
 
 #[derive(Parser)]
 #[command(name = "security-service")]
@@ -28,14 +28,8 @@ enum Commands {
 
 #[tokio::main]
 async fn main() -> Result<()> {
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "security_service=debug,tower_http=debug".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    // Initialize tracing, exporting spans when OTEL_EXPORTER_OTLP_ENDPOINT is set
+    let _telemetry = init_tracing("security-service", "security_service=debug,tower_http=debug")?;
 
     let cli = Cli::parse();
     let config = SecurityConfig::from_env()?;
@@ -3,12 +3,16 @@ use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use uuid::Uuid;
 use std::collections::HashMap;
+use adx_shared::audit::{AuditCategory, AuditEvent, AuditOutcome as EventOutcome};
+use adx_shared::dlp::DlpRule;
 
 // Audit Log Models
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct AuditLog {
     pub id: Uuid,
     pub tenant_id: String,
+    /// Service the event happened in
+    pub service: String,
     pub user_id: Option<String>,
     pub session_id: Option<String>,
     pub event_type: String,
@@ -23,6 +27,12 @@ pub struct AuditLog {
     pub details: serde_json::Value,
     pub risk_score: Option<i32>,
     pub created_at: DateTime<Utc>,
+    /// Position in the tenant's hash chain; None for events logged before
+    /// events were chained
+    pub sequence: Option<i64>,
+    pub previous_hash: Option<String>,
+    /// Hex SHA-256 over the event and `previous_hash`
+    pub hash: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
@@ -64,6 +74,84 @@ pub struct ComplianceReport {
     pub generated_by: String,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    /// "tenant", or "platform" for reports covering every tenant
+    pub scope: String,
+    pub template_version: Option<String>,
+    pub compliance_score: Option<f32>,
+    /// Assessed controls of the template
+    pub controls: serde_json::Value,
+    /// Evidence the controls were assessed on
+    pub evidence: serde_json::Value,
+}
+
+/// Kinds of evidence gathered for a compliance report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum EvidenceKind {
+    /// Privileged grants in the period, how their alerts were triaged and
+    /// the access review campaigns completed
+    AccessReviews,
+    /// Data and audit log retention policies in force
+    RetentionStatus,
+    /// Encryption algorithm, key rotation and audit log encryption
+    EncryptionPosture,
+    /// A sample of audit events and the integrity of the audit chain
+    AuditSamples,
+    /// Security alerts raised in the period and their handling
+    SecurityAlerts,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlTemplate {
+    /// Identifier in the framework, e.g. "CC6.1"
+    pub id: String,
+    pub title: String,
+    pub description: String,
+    /// Evidence that must all pass for the control to pass
+    pub evidence: Vec<EvidenceKind>,
+}
+
+/// The controls of a framework a report is assessed against
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceTemplate {
+    pub report_type: ComplianceReportType,
+    pub name: String,
+    pub version: String,
+    pub controls: Vec<ControlTemplate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EvidenceItem {
+    pub kind: EvidenceKind,
+    pub passed: bool,
+    pub summary: String,
+    pub data: serde_json::Value,
+    pub collected_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ControlResult {
+    pub id: String,
+    pub title: String,
+    pub passed: bool,
+    pub failing_evidence: Vec<EvidenceKind>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ComplianceAssessment {
+    pub controls: Vec<ControlResult>,
+    pub compliance_score: f32,
+    pub status: ComplianceStatus,
+    pub risk_level: RiskLevel,
+    pub recommendations: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerateComplianceReportRequest {
+    pub report_type: ComplianceReportType,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub requested_by: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, sqlx::Type)]
@@ -330,10 +418,124 @@ pub enum SecurityEventStatus {
     Suppressed,
 }
 
+// Security Alert Models
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SecurityAlert {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub rule: AlertRule,
+    pub severity: SecurityEventSeverity,
+    pub status: AlertStatus,
+    pub title: String,
+    pub description: String,
+    pub dedup_key: String,
+    pub user_id: Option<String>,
+    pub event_ids: serde_json::Value,
+    pub event_count: i32,
+    pub details: serde_json::Value,
+    pub first_seen_at: DateTime<Utc>,
+    pub last_seen_at: DateTime<Utc>,
+    pub assigned_to: Option<String>,
+    pub assigned_at: Option<DateTime<Utc>>,
+    pub acknowledged_by: Option<String>,
+    pub acknowledged_at: Option<DateTime<Utc>>,
+    pub resolved_by: Option<String>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub resolution_notes: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "alert_rule", rename_all = "lowercase")]
+#[serde(rename_all = "snake_case")]
+pub enum AlertRule {
+    /// A user downloading far more than usual, or more than the limit
+    MassDownload,
+    /// Privileged roles or permissions granted, or granted to oneself
+    PrivilegeEscalation,
+    /// Repeated failed sign-ins for a user or from an address
+    FailedLoginBurst,
+    /// Sign-in from an address the user has not used before
+    NewLoginLocation,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "alert_status", rename_all = "lowercase")]
+#[serde(rename_all = "snake_case")]
+pub enum AlertStatus {
+    Open,
+    Acknowledged,
+    Resolved,
+    FalsePositive,
+}
+
+impl AlertStatus {
+    /// Open and acknowledged alerts still collect matching events
+    pub fn is_active(&self) -> bool {
+        matches!(self, AlertStatus::Open | AlertStatus::Acknowledged)
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecurityAlertFilter {
+    pub status: Option<AlertStatus>,
+    pub severity: Option<SecurityEventSeverity>,
+    pub rule: Option<AlertRule>,
+    pub assigned_to: Option<String>,
+    pub user_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SecurityAlertListResponse {
+    pub alerts: Vec<SecurityAlert>,
+    pub total_count: i64,
+    pub page: i32,
+    pub page_size: i32,
+}
+
+/// Alerts raised in a period, by rule, severity and status
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AlertCount {
+    pub rule: AlertRule,
+    pub severity: SecurityEventSeverity,
+    pub status: AlertStatus,
+    pub count: i64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AcknowledgeAlertRequest {
+    pub acknowledged_by: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AssignAlertRequest {
+    pub assigned_to: String,
+    pub assigned_by: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ResolveAlertRequest {
+    pub resolved_by: String,
+    /// Marks the alert as a false positive rather than a handled incident
+    #[serde(default)]
+    pub false_positive: bool,
+    pub notes: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ReopenAlertRequest {
+    pub reopened_by: String,
+    pub reason: Option<String>,
+}
+
 // Request/Response Models
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CreateAuditLogRequest {
+    /// Set by the reporting service so a redelivered event is stored once
+    pub event_id: Option<Uuid>,
     pub tenant_id: String,
+    pub service: String,
     pub user_id: Option<String>,
     pub session_id: Option<String>,
     pub event_type: String,
@@ -346,6 +548,125 @@ pub struct CreateAuditLogRequest {
     pub user_agent: Option<String>,
     pub request_id: Option<String>,
     pub details: serde_json::Value,
+    /// When the reporting service saw it happen; defaults to when it is logged
+    pub occurred_at: Option<DateTime<Utc>>,
+}
+
+impl From<AuditEvent> for CreateAuditLogRequest {
+    fn from(event: AuditEvent) -> Self {
+        let (event_type, event_category) = match event.category {
+            AuditCategory::Authentication => ("authentication", AuditEventCategory::Authentication),
+            AuditCategory::Authorization => ("authorization", AuditEventCategory::Authorization),
+            AuditCategory::DataAccess => ("data_access", AuditEventCategory::DataAccess),
+            AuditCategory::DataModification => ("data_modification", AuditEventCategory::DataModification),
+            AuditCategory::SystemAccess => ("system_access", AuditEventCategory::SystemAccess),
+            AuditCategory::Configuration => ("configuration", AuditEventCategory::Configuration),
+            AuditCategory::Security => ("security", AuditEventCategory::Security),
+            AuditCategory::Compliance => ("compliance", AuditEventCategory::Compliance),
+            AuditCategory::Privacy => ("privacy", AuditEventCategory::Privacy),
+            AuditCategory::Administrative => ("administrative", AuditEventCategory::Administrative),
+        };
+        let outcome = match event.outcome {
+            EventOutcome::Success => AuditOutcome::Success,
+            EventOutcome::Failure => AuditOutcome::Failure,
+            EventOutcome::Warning => AuditOutcome::Warning,
+            EventOutcome::Error => AuditOutcome::Error,
+        };
+
+        Self {
+            event_id: Some(event.id),
+            tenant_id: event.tenant_id,
+            service: event.service,
+            user_id: event.actor_id,
+            session_id: event.session_id,
+            event_type: event_type.to_string(),
+            event_category,
+            resource_type: event.resource_type,
+            resource_id: event.resource_id,
+            action: event.action,
+            outcome,
+            ip_address: event.ip_address,
+            user_agent: event.user_agent,
+            request_id: event.request_id,
+            details: if event.details.is_null() { serde_json::json!({}) } else { event.details },
+            occurred_at: Some(event.occurred_at),
+        }
+    }
+}
+
+/// Events delivered by `adx_shared::audit::AuditEmitter`
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IngestAuditEventsRequest {
+    pub events: Vec<AuditEvent>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IngestAuditEventsResponse {
+    pub accepted: usize,
+    pub event_ids: Vec<Uuid>,
+}
+
+/// Narrows a tenant's audit log; every field is optional
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AuditLogFilter {
+    pub start_date: Option<DateTime<Utc>>,
+    pub end_date: Option<DateTime<Utc>>,
+    pub event_category: Option<AuditEventCategory>,
+    pub user_id: Option<String>,
+    pub resource_type: Option<String>,
+    pub resource_id: Option<String>,
+    pub service: Option<String>,
+    pub action: Option<String>,
+    pub outcome: Option<AuditOutcome>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditChainVerification {
+    pub tenant_id: String,
+    pub valid: bool,
+    pub verified_events: i64,
+    pub first_sequence: Option<i64>,
+    pub last_sequence: Option<i64>,
+    /// First event whose hash or link does not match
+    pub broken_at_sequence: Option<i64>,
+    pub reason: Option<String>,
+    pub verified_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AuditRetentionPolicy {
+    pub tenant_id: String,
+    pub retention_days: i32,
+    pub updated_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SetAuditRetentionPolicyRequest {
+    pub retention_days: i32,
+    pub updated_by: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AuditRetentionRun {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub cutoff: DateTime<Utc>,
+    pub deleted_count: i64,
+    pub last_deleted_sequence: Option<i64>,
+    pub last_deleted_hash: Option<String>,
+    pub ran_at: DateTime<Utc>,
+}
+
+/// The retention a tenant's audit log is under
+#[derive(Debug, Serialize, Deserialize)]
+pub struct AuditRetentionSettings {
+    pub tenant_id: String,
+    pub retention_days: i32,
+    /// No tenant policy, so the service default applies
+    pub is_default: bool,
+    pub last_run: Option<AuditRetentionRun>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -437,4 +758,542 @@ pub struct DataRetentionSummary {
     pub scheduled_jobs: i32,
     pub records_to_delete: i64,
     pub next_cleanup: Option<DateTime<Utc>>,
-}
\ No newline at end of file
+}
+// Data Destruction Models
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DataDestructionCertificate {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub tenant_name: String,
+    pub requested_by: String,
+    pub soft_deleted_at: DateTime<Utc>,
+    pub purged_at: DateTime<Utc>,
+    pub purged_services: serde_json::Value,
+    /// Hex SHA-256 of the canonical certificate body
+    pub digest: String,
+    /// Hex Ed25519 signature over the digest
+    pub signature: String,
+    pub signing_key_id: String,
+    pub issued_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PurgedServiceRecord {
+    pub service: String,
+    pub records_deleted: i64,
+    pub purged_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssueDestructionCertificateRequest {
+    /// Chosen by the caller so a retried request returns the same certificate
+    pub certificate_id: Uuid,
+    pub tenant_id: String,
+    pub tenant_name: String,
+    pub requested_by: String,
+    pub soft_deleted_at: DateTime<Utc>,
+    pub purged_at: DateTime<Utc>,
+    pub purged_services: Vec<PurgedServiceRecord>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DestructionCertificateVerification {
+    pub certificate_id: Uuid,
+    pub valid: bool,
+    pub signing_key_id: String,
+    pub public_key: String,
+}
+
+// Key Lifecycle Models
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "key_purpose", rename_all = "lowercase")]
+#[serde(rename_all = "snake_case")]
+pub enum KeyPurpose {
+    /// Signs the platform's access tokens
+    JwtSigning,
+    /// Encrypts a tenant's data at rest
+    TenantData,
+    /// Signs a lifecycle webhook's deliveries
+    WebhookSigning,
+}
+
+impl KeyPurpose {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            KeyPurpose::JwtSigning => "jwt_signing",
+            KeyPurpose::TenantData => "tenant_data",
+            KeyPurpose::WebhookSigning => "webhook_signing",
+        }
+    }
+
+    /// Data keys never leave security-service; signing keys are handed to validators
+    pub fn is_published(&self) -> bool {
+        !matches!(self, KeyPurpose::TenantData)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "key_status", rename_all = "lowercase")]
+#[serde(rename_all = "snake_case")]
+pub enum KeyStatus {
+    /// Staged for validators; not used to sign or encrypt yet
+    Pending,
+    Active,
+    /// Replaced, still accepted until `retire_after`
+    Retiring,
+    /// No longer accepted; retired data keys still decrypt
+    Retired,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ManagedKey {
+    pub id: Uuid,
+    pub purpose: KeyPurpose,
+    pub owner_id: String,
+    pub kid: String,
+    pub status: KeyStatus,
+    /// Encrypted with the master key
+    #[serde(skip_serializing)]
+    pub material: Vec<u8>,
+    pub rotated_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub activated_at: Option<DateTime<Utc>>,
+    pub retiring_at: Option<DateTime<Utc>>,
+    pub retire_after: Option<DateTime<Utc>>,
+    pub retired_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct KeyRotationSchedule {
+    pub purpose: KeyPurpose,
+    pub owner_id: String,
+    pub rotation_days: i32,
+    /// How long a replaced key stays valid
+    pub overlap_hours: i32,
+    pub enabled: bool,
+    pub last_rotated_at: Option<DateTime<Utc>>,
+    pub next_rotation_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetKeyRotationScheduleRequest {
+    pub rotation_days: i32,
+    /// Defaults to the purpose's configured overlap
+    pub overlap_hours: Option<i32>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RotateKeyRequest {
+    pub requested_by: String,
+}
+
+/// A key's versions and its rotation schedule
+#[derive(Debug, Serialize, Deserialize)]
+pub struct KeyLifecycle {
+    pub purpose: KeyPurpose,
+    pub owner_id: String,
+    pub keys: Vec<ManagedKey>,
+    pub schedule: Option<KeyRotationSchedule>,
+}
+
+/// What one pass over the key lifecycle changed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct KeyMaintenanceSummary {
+    pub staged: usize,
+    pub activated: usize,
+    pub retired: usize,
+    pub failed: usize,
+}
+
+// DLP Models
+#[derive(Debug, Clone, FromRow)]
+pub struct DlpPolicyRecord {
+    pub tenant_id: String,
+    pub enabled: bool,
+    pub rules: serde_json::Value,
+    pub updated_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetDlpPolicyRequest {
+    pub enabled: bool,
+    pub rules: Vec<DlpRule>,
+    pub updated_by: Option<String>,
+}
+
+// Network Policy Models
+#[derive(Debug, Clone, FromRow)]
+pub struct NetworkPolicyRecord {
+    pub tenant_id: String,
+    pub enabled: bool,
+    pub allowed_cidrs: Vec<String>,
+    pub allowed_countries: Vec<String>,
+    pub blocked_countries: Vec<String>,
+    pub block_tor: bool,
+    pub block_vpn: bool,
+    pub updated_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetNetworkPolicyRequest {
+    pub enabled: bool,
+    #[serde(default)]
+    pub allowed_cidrs: Vec<String>,
+    #[serde(default)]
+    pub allowed_countries: Vec<String>,
+    #[serde(default)]
+    pub blocked_countries: Vec<String>,
+    #[serde(default)]
+    pub block_tor: bool,
+    #[serde(default)]
+    pub block_vpn: bool,
+    pub updated_by: Option<String>,
+}
+
+/// A feed's full list of ranges for one anonymizer category
+#[derive(Debug, Deserialize)]
+pub struct ReplaceAnonymizerRangesRequest {
+    pub ranges: Vec<String>,
+    pub source: String,
+}
+
+#[derive(Debug, Clone, Serialize)]
+pub struct AnonymizerRangeSummary {
+    pub category: String,
+    pub range_count: i64,
+    pub source: String,
+    pub updated_at: DateTime<Utc>,
+}
+
+// Access Review Models
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "access_review_status", rename_all = "lowercase")]
+#[serde(rename_all = "snake_case")]
+pub enum AccessReviewStatus {
+    Open,
+    Completed,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "access_review_subject", rename_all = "lowercase")]
+#[serde(rename_all = "snake_case")]
+pub enum AccessReviewSubject {
+    /// A role held by a user
+    UserRole,
+    /// The capabilities granted to an installed module
+    ModuleGrant,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "access_review_decision", rename_all = "lowercase")]
+#[serde(rename_all = "snake_case")]
+pub enum AccessReviewDecision {
+    Pending,
+    Approved,
+    Revoked,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AccessReviewCampaign {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub name: String,
+    pub status: AccessReviewStatus,
+    pub deadline: DateTime<Utc>,
+    /// Revoke items nobody attested to when the campaign closes
+    pub auto_revoke: bool,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    #[serde(skip_serializing)]
+    pub evidence: Option<serde_json::Value>,
+    pub evidence_hash: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AccessReviewItem {
+    pub id: Uuid,
+    pub campaign_id: Uuid,
+    pub tenant_id: String,
+    pub subject_type: AccessReviewSubject,
+    /// The user, or the module instance holding the grant
+    pub subject_id: String,
+    pub subject_name: String,
+    /// The role, or the granted module version
+    pub entitlement: String,
+    pub details: serde_json::Value,
+    pub decision: AccessReviewDecision,
+    pub decided_by: Option<String>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub comment: Option<String>,
+    pub revoked_at: Option<DateTime<Utc>>,
+    pub auto_revoked: bool,
+    pub revocation_error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AccessReviewSchedule {
+    pub tenant_id: String,
+    pub interval_days: i32,
+    /// How long reviewers have to attest once a campaign starts
+    pub review_days: i32,
+    pub auto_revoke: bool,
+    pub enabled: bool,
+    pub last_campaign_at: Option<DateTime<Utc>>,
+    pub next_campaign_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StartAccessReviewRequest {
+    pub name: Option<String>,
+    /// Defaults to the configured review period
+    pub review_days: Option<i32>,
+    pub auto_revoke: Option<bool>,
+    pub created_by: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessAttestation {
+    pub item_id: Uuid,
+    /// Approved keeps the access; revoked removes it straight away
+    pub decision: AccessReviewDecision,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AttestAccessRequest {
+    pub reviewer: String,
+    pub attestations: Vec<AccessAttestation>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CloseAccessReviewRequest {
+    pub closed_by: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetAccessReviewScheduleRequest {
+    pub interval_days: i32,
+    pub review_days: Option<i32>,
+    pub auto_revoke: Option<bool>,
+    pub enabled: Option<bool>,
+}
+
+/// A campaign with how far its review has got
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessReviewSummary {
+    pub campaign: AccessReviewCampaign,
+    pub total_items: i64,
+    pub pending: i64,
+    pub approved: i64,
+    pub revoked: i64,
+    pub auto_revoked: i64,
+    pub revocation_failures: i64,
+}
+
+/// What one pass over the access review schedules changed
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AccessReviewMaintenanceSummary {
+    pub started: usize,
+    pub completed: usize,
+    pub failed: usize,
+}
+
+// Incident Response Models
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "incident_status", rename_all = "lowercase")]
+#[serde(rename_all = "snake_case")]
+pub enum IncidentStatus {
+    Open,
+    /// The attacker's access has been cut off; the cause is still being fixed
+    Contained,
+    Resolved,
+    /// Resolved and its postmortem is done
+    Closed,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "containment_action_type", rename_all = "lowercase")]
+#[serde(rename_all = "snake_case")]
+pub enum ContainmentActionType {
+    /// Sign a user out everywhere; target is the user
+    RevokeSessions,
+    /// Disable a user's API keys; target is the user
+    DisableApiKeys,
+    /// Deactivate a module instance; target is the instance
+    QuarantineModule,
+    /// Suspend a tenant; target is the tenant
+    LockTenant,
+}
+
+impl ContainmentActionType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ContainmentActionType::RevokeSessions => "revoke_sessions",
+            ContainmentActionType::DisableApiKeys => "disable_api_keys",
+            ContainmentActionType::QuarantineModule => "quarantine_module",
+            ContainmentActionType::LockTenant => "lock_tenant",
+        }
+    }
+
+    /// Actions that take a module or a whole tenant offline need a second
+    /// person to approve them; cutting off one user's access does not
+    pub fn requires_approval(&self) -> bool {
+        matches!(self, ContainmentActionType::QuarantineModule | ContainmentActionType::LockTenant)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, sqlx::Type)]
+#[sqlx(type_name = "containment_action_status", rename_all = "lowercase")]
+#[serde(rename_all = "snake_case")]
+pub enum ContainmentActionStatus {
+    PendingApproval,
+    Approved,
+    Rejected,
+    /// Nobody approved it in time
+    Expired,
+    Executing,
+    Completed,
+    Failed,
+}
+
+impl ContainmentActionStatus {
+    pub fn is_final(&self) -> bool {
+        matches!(
+            self,
+            ContainmentActionStatus::Rejected
+                | ContainmentActionStatus::Expired
+                | ContainmentActionStatus::Completed
+                | ContainmentActionStatus::Failed
+        )
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, Default)]
+pub struct IncidentPostmortem {
+    pub summary: String,
+    pub root_cause: String,
+    pub impact: String,
+    #[serde(default)]
+    pub lessons_learned: Vec<String>,
+    #[serde(default)]
+    pub follow_up_actions: Vec<String>,
+    pub author: String,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct SecurityIncident {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub title: String,
+    pub description: String,
+    pub severity: SecurityEventSeverity,
+    pub status: IncidentStatus,
+    pub alert_ids: Vec<Uuid>,
+    pub opened_by: String,
+    pub assigned_to: Option<String>,
+    pub opened_at: DateTime<Utc>,
+    pub contained_at: Option<DateTime<Utc>>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub resolution_notes: Option<String>,
+    pub postmortem: Option<serde_json::Value>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct ContainmentAction {
+    pub id: Uuid,
+    pub incident_id: Uuid,
+    pub tenant_id: String,
+    pub action_type: ContainmentActionType,
+    pub target: String,
+    pub reason: String,
+    pub requires_approval: bool,
+    pub status: ContainmentActionStatus,
+    pub requested_by: String,
+    pub requested_at: DateTime<Utc>,
+    pub decided_by: Option<String>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub decision_comment: Option<String>,
+    pub executed_at: Option<DateTime<Utc>>,
+    pub result: Option<serde_json::Value>,
+    pub error: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct IncidentTimelineEntry {
+    pub id: Uuid,
+    pub incident_id: Uuid,
+    pub tenant_id: String,
+    pub entry_type: String,
+    pub actor: String,
+    pub message: String,
+    pub details: serde_json::Value,
+    pub occurred_at: DateTime<Utc>,
+}
+
+/// An incident with its containment actions and timeline
+#[derive(Debug, Serialize, Deserialize)]
+pub struct IncidentDetail {
+    pub incident: SecurityIncident,
+    pub actions: Vec<ContainmentAction>,
+    pub timeline: Vec<IncidentTimelineEntry>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OpenIncidentRequest {
+    /// Alerts the incident is opened from; the first one titles it by default
+    pub alert_ids: Vec<Uuid>,
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Defaults to the most severe alert's severity
+    pub severity: Option<SecurityEventSeverity>,
+    pub opened_by: String,
+    pub assigned_to: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct IncidentFilter {
+    pub status: Option<IncidentStatus>,
+    pub severity: Option<SecurityEventSeverity>,
+    pub assigned_to: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentNoteRequest {
+    pub author: String,
+    pub message: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RequestContainmentRequest {
+    pub action_type: ContainmentActionType,
+    pub target: String,
+    pub reason: String,
+    pub requested_by: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecideContainmentRequest {
+    pub decided_by: String,
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateIncidentStatusRequest {
+    pub status: IncidentStatus,
+    pub updated_by: String,
+    pub notes: Option<String>,
+}
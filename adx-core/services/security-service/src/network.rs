@@ -0,0 +1,189 @@
+use crate::{
+    audit::AuditService,
+    error::{SecurityError, SecurityResult},
+    models::{
+        AnonymizerRangeSummary, AuditLogFilter, AuditLogResponse, AuditOutcome, NetworkPolicyRecord,
+        ReplaceAnonymizerRangesRequest, SetNetworkPolicyRequest,
+    },
+    repositories::NetworkPolicyRepository,
+};
+use adx_shared::network_policy::{
+    AnonymizerCategory, AnonymizerRange, IpCidr, NetworkPolicy, NETWORK_ACCESS_DENIED_ACTION,
+};
+use adx_shared::ServiceError;
+use chrono::Utc;
+use serde_json::json;
+use std::sync::Arc;
+use tracing::{info, warn};
+
+// Tenant network policies. Security-service keeps the policies and the
+// anonymizer ranges; api-gateway enforces them and reports each refused
+// request to the audit log.
+
+pub const MAX_ALLOWED_CIDRS: usize = 500;
+pub const MAX_COUNTRIES: usize = 250;
+
+pub struct NetworkPolicyService {
+    repository: Arc<NetworkPolicyRepository>,
+    audit_service: Arc<AuditService>,
+}
+
+impl NetworkPolicyService {
+    pub fn new(repository: Arc<NetworkPolicyRepository>, audit_service: Arc<AuditService>) -> Self {
+        Self {
+            repository,
+            audit_service,
+        }
+    }
+
+    /// The tenant's policy; an unrestricted one when it has not set any
+    pub async fn get_policy(&self, tenant_id: &str) -> SecurityResult<NetworkPolicy> {
+        Ok(match self.repository.get_policy(tenant_id).await? {
+            Some(record) => to_policy(record),
+            None => NetworkPolicy::unrestricted(tenant_id),
+        })
+    }
+
+    pub async fn set_policy(&self, tenant_id: &str, request: SetNetworkPolicyRequest) -> SecurityResult<NetworkPolicy> {
+        if request.allowed_cidrs.len() > MAX_ALLOWED_CIDRS {
+            return Err(SecurityError::Validation(format!("A network policy allows at most {} ranges", MAX_ALLOWED_CIDRS)));
+        }
+        if request.allowed_countries.len() + request.blocked_countries.len() > MAX_COUNTRIES {
+            return Err(SecurityError::Validation(format!("A network policy lists at most {} countries", MAX_COUNTRIES)));
+        }
+
+        let mut candidate = NetworkPolicy {
+            tenant_id: tenant_id.to_string(),
+            enabled: request.enabled,
+            allowed_cidrs: request.allowed_cidrs,
+            allowed_countries: request.allowed_countries.iter().map(|country| country.trim().to_ascii_uppercase()).collect(),
+            blocked_countries: request.blocked_countries.iter().map(|country| country.trim().to_ascii_uppercase()).collect(),
+            block_tor: request.block_tor,
+            block_vpn: request.block_vpn,
+            updated_by: request.updated_by,
+            updated_at: None,
+        };
+        // Store what the gateway will enforce, so a range like 10.1.2.3/8 reads as 10.0.0.0/8
+        candidate.allowed_cidrs = candidate
+            .allowed_cidrs
+            .iter()
+            .map(|cidr| cidr.parse::<IpCidr>().map(|cidr| cidr.to_string()))
+            .collect::<Result<Vec<_>, _>>()
+            .map_err(validation_error)?;
+        candidate.compile().map_err(validation_error)?;
+        if candidate.allowed_countries.iter().any(|country| candidate.blocked_countries.contains(country)) {
+            return Err(SecurityError::Validation("A country cannot be both allowed and blocked".to_string()));
+        }
+
+        let record = self.repository.upsert_policy(&NetworkPolicyRecord {
+            tenant_id: tenant_id.to_string(),
+            enabled: candidate.enabled,
+            allowed_cidrs: candidate.allowed_cidrs,
+            allowed_countries: candidate.allowed_countries,
+            blocked_countries: candidate.blocked_countries,
+            block_tor: candidate.block_tor,
+            block_vpn: candidate.block_vpn,
+            updated_by: candidate.updated_by,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }).await?;
+        let policy = to_policy(record);
+
+        self.audit_service.log_compliance_event(
+            tenant_id,
+            "network_policy",
+            "update_network_policy",
+            AuditOutcome::Success,
+            json!({
+                "enabled": policy.enabled,
+                "allowed_cidrs": policy.allowed_cidrs,
+                "allowed_countries": policy.allowed_countries,
+                "blocked_countries": policy.blocked_countries,
+                "block_tor": policy.block_tor,
+                "block_vpn": policy.block_vpn,
+                "updated_by": policy.updated_by,
+            }),
+        ).await?;
+
+        Ok(policy)
+    }
+
+    /// Requests the gateway refused for the tenant, from its audit log
+    pub async fn list_denials(
+        &self,
+        tenant_id: &str,
+        mut filter: AuditLogFilter,
+        page: i32,
+        page_size: i32,
+    ) -> SecurityResult<AuditLogResponse> {
+        filter.action = Some(NETWORK_ACCESS_DENIED_ACTION.to_string());
+        self.audit_service.get_audit_logs(tenant_id, &filter, page, page_size).await
+    }
+
+    pub async fn anonymizer_ranges(&self) -> SecurityResult<Vec<AnonymizerRange>> {
+        let rows = self.repository.list_anonymizer_ranges().await?;
+
+        Ok(rows
+            .into_iter()
+            .filter_map(|(cidr, category)| match category.as_str() {
+                "tor" => Some(AnonymizerRange { cidr, category: AnonymizerCategory::Tor }),
+                "vpn" => Some(AnonymizerRange { cidr, category: AnonymizerCategory::Vpn }),
+                other => {
+                    warn!(category = other, "Unknown anonymizer category");
+                    None
+                }
+            })
+            .collect())
+    }
+
+    /// Replace a category's ranges with a feed's latest list. Entries that
+    /// are not valid ranges are dropped rather than failing the whole feed.
+    pub async fn replace_anonymizer_ranges(
+        &self,
+        category: AnonymizerCategory,
+        request: ReplaceAnonymizerRangesRequest,
+    ) -> SecurityResult<AnonymizerRangeSummary> {
+        if request.source.trim().is_empty() {
+            return Err(SecurityError::Validation("The source of the ranges is required".to_string()));
+        }
+
+        let total = request.ranges.len();
+        let ranges: Vec<String> = request
+            .ranges
+            .iter()
+            .filter_map(|range| range.parse::<IpCidr>().ok())
+            .map(|cidr| cidr.to_string())
+            .collect();
+        if ranges.len() < total {
+            warn!(category = category.as_str(), dropped = total - ranges.len(), "Dropped invalid anonymizer ranges");
+        }
+
+        let summary = self.repository
+            .replace_anonymizer_ranges(category.as_str(), &ranges, request.source.trim())
+            .await?;
+        info!(category = category.as_str(), ranges = summary.range_count, source = %summary.source, "Replaced anonymizer ranges");
+
+        Ok(summary)
+    }
+}
+
+fn validation_error(error: ServiceError) -> SecurityError {
+    match error {
+        ServiceError::Validation(message) => SecurityError::Validation(message),
+        other => SecurityError::Validation(other.to_string()),
+    }
+}
+
+fn to_policy(record: NetworkPolicyRecord) -> NetworkPolicy {
+    NetworkPolicy {
+        tenant_id: record.tenant_id,
+        enabled: record.enabled,
+        allowed_cidrs: record.allowed_cidrs,
+        allowed_countries: record.allowed_countries,
+        blocked_countries: record.blocked_countries,
+        block_tor: record.block_tor,
+        block_vpn: record.block_vpn,
+        updated_by: record.updated_by,
+        updated_at: Some(record.updated_at),
+    }
+}
@@ -1,11 +1,17 @@
 use crate::{
+    audit::{chain_hash, GENESIS_HASH},
     error::{SecurityError, SecurityResult},
     models::{
-        AuditLog, AuditEventCategory, AuditOutcome, ComplianceReport, ComplianceReportType,
-        ComplianceStatus, GdprRequest, GdprRequestType, GdprRequestStatus, DataRetentionPolicy,
+        AuditLog, AuditEventCategory, AuditOutcome, AuditLogFilter, AuditRetentionPolicy,
+        AuditRetentionRun, AlertCount, AlertRule, AlertStatus, SecurityAlert, SecurityAlertFilter,
+        ComplianceReport, ComplianceReportType, ComplianceStatus, RiskLevel, GdprRequest, GdprRequestType, GdprRequestStatus, DataRetentionPolicy,
         DataRetentionJob, RetentionJobStatus, SecurityScan, ScanType, ScanStatus, Vulnerability,
         ZeroTrustPolicy, ZeroTrustPolicyType, SecurityEvent, SecurityEventType,
-        SecurityEventSeverity, SecurityEventStatus
+        SecurityEventSeverity, SecurityEventStatus, DataDestructionCertificate, KeyPurpose, KeyStatus,
+        KeyRotationSchedule, ManagedKey, DlpPolicyRecord, NetworkPolicyRecord, AnonymizerRangeSummary,
+        AccessReviewCampaign, AccessReviewDecision, AccessReviewItem, AccessReviewSchedule, AccessReviewStatus,
+        AccessReviewSubject, SecurityIncident, IncidentStatus, IncidentFilter, ContainmentAction,
+        ContainmentActionType, ContainmentActionStatus, IncidentTimelineEntry
     },
 };
 use chrono::{DateTime, Utc};
@@ -25,20 +31,57 @@ impl AuditRepository {
         Self { pool }
     }
 
-    pub async fn batch_insert_logs(&self, logs: Vec<AuditLog>) -> SecurityResult<()> {
+    /// Store a batch, appending each event to its tenant's hash chain. The
+    /// chain head is locked for the rest of the transaction, so concurrent
+    /// batches for one tenant are linked one after the other. Events already
+    /// stored, e.g. from a retried delivery, are skipped.
+    pub async fn batch_insert_logs(&self, mut logs: Vec<AuditLog>) -> SecurityResult<()> {
+        logs.sort_by(|a, b| a.tenant_id.cmp(&b.tenant_id));
         let mut tx = self.pool.begin().await?;
 
-        for log in logs {
-            sqlx::query!(
+        let mut head: Option<(String, i64, String)> = None;
+        for mut log in logs {
+            if head.as_ref().map_or(true, |(tenant_id, _, _)| *tenant_id != log.tenant_id) {
+                if let Some((tenant_id, sequence, hash)) = head.take() {
+                    Self::advance_chain_head(&mut tx, &tenant_id, sequence, &hash).await?;
+                }
+
+                sqlx::query!(
+                    "INSERT INTO audit_chain_heads (tenant_id, sequence, hash) VALUES ($1, 0, $2)
+                     ON CONFLICT (tenant_id) DO NOTHING",
+                    log.tenant_id,
+                    GENESIS_HASH
+                )
+                .execute(&mut *tx)
+                .await?;
+
+                let current = sqlx::query!(
+                    "SELECT sequence, hash FROM audit_chain_heads WHERE tenant_id = $1 FOR UPDATE",
+                    log.tenant_id
+                )
+                .fetch_one(&mut *tx)
+                .await?;
+
+                head = Some((log.tenant_id.clone(), current.sequence, current.hash));
+            }
+
+            let (_, sequence, hash) = head.as_mut().expect("chain head loaded for tenant");
+            log.sequence = Some(*sequence + 1);
+            log.previous_hash = Some(hash.clone());
+            log.hash = Some(chain_hash(&log)?);
+
+            let result = sqlx::query!(
                 r#"
                 INSERT INTO audit_logs (
-                    id, tenant_id, user_id, session_id, event_type, event_category,
+                    id, tenant_id, service, user_id, session_id, event_type, event_category,
                     resource_type, resource_id, action, outcome, ip_address, user_agent,
-                    request_id, details, risk_score, created_at
-                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+                    request_id, details, risk_score, created_at, sequence, previous_hash, hash
+                ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19, $20)
+                ON CONFLICT (id) DO NOTHING
                 "#,
                 log.id,
                 log.tenant_id,
+                log.service,
                 log.user_id,
                 log.session_id,
                 log.event_type,
@@ -52,51 +95,93 @@ impl AuditRepository {
                 log.request_id,
                 log.details,
                 log.risk_score,
-                log.created_at
+                log.created_at,
+                log.sequence,
+                log.previous_hash,
+                log.hash
             )
             .execute(&mut *tx)
             .await?;
+
+            if result.rows_affected() > 0 {
+                *sequence += 1;
+                *hash = log.hash.unwrap_or_default();
+            }
+        }
+
+        if let Some((tenant_id, sequence, hash)) = head {
+            Self::advance_chain_head(&mut tx, &tenant_id, sequence, &hash).await?;
         }
 
         tx.commit().await?;
         Ok(())
     }
 
-    pub async fn get_audit_logs(
-        &self,
+    async fn advance_chain_head(
+        tx: &mut sqlx::Transaction<'_, sqlx::Postgres>,
         tenant_id: &str,
-        start_date: Option<DateTime<Utc>>,
-        end_date: Option<DateTime<Utc>>,
-        event_category: Option<AuditEventCategory>,
-        user_id: Option<&str>,
-        resource_type: Option<&str>,
-        page: i32,
-        page_size: i32,
-    ) -> SecurityResult<Vec<AuditLog>> {
-        let offset = (page - 1) * page_size;
+        sequence: i64,
+        hash: &str,
+    ) -> SecurityResult<()> {
+        sqlx::query!(
+            "UPDATE audit_chain_heads SET sequence = $2, hash = $3, updated_at = NOW() WHERE tenant_id = $1",
+            tenant_id,
+            sequence,
+            hash
+        )
+        .execute(&mut **tx)
+        .await?;
 
-        let mut query = sqlx::QueryBuilder::new(
-            "SELECT id, tenant_id, user_id, session_id, event_type, event_category,
-             resource_type, resource_id, action, outcome, ip_address, user_agent,
-             request_id, details, risk_score, created_at FROM audit_logs WHERE tenant_id = "
-        );
-        query.push_bind(tenant_id);
+        Ok(())
+    }
 
-        if let Some(start) = start_date {
+    fn push_filter(query: &mut sqlx::QueryBuilder<'_, sqlx::Postgres>, filter: &AuditLogFilter) {
+        if let Some(start) = filter.start_date {
             query.push(" AND created_at >= ").push_bind(start);
         }
-        if let Some(end) = end_date {
+        if let Some(end) = filter.end_date {
             query.push(" AND created_at <= ").push_bind(end);
         }
-        if let Some(category) = event_category {
+        if let Some(category) = filter.event_category.clone() {
             query.push(" AND event_category = ").push_bind(category);
         }
-        if let Some(uid) = user_id {
+        if let Some(uid) = filter.user_id.clone() {
             query.push(" AND user_id = ").push_bind(uid);
         }
-        if let Some(rtype) = resource_type {
+        if let Some(rtype) = filter.resource_type.clone() {
             query.push(" AND resource_type = ").push_bind(rtype);
         }
+        if let Some(rid) = filter.resource_id.clone() {
+            query.push(" AND resource_id = ").push_bind(rid);
+        }
+        if let Some(service) = filter.service.clone() {
+            query.push(" AND service = ").push_bind(service);
+        }
+        if let Some(action) = filter.action.clone() {
+            query.push(" AND action = ").push_bind(action);
+        }
+        if let Some(outcome) = filter.outcome.clone() {
+            query.push(" AND outcome = ").push_bind(outcome);
+        }
+    }
+
+    pub async fn get_audit_logs(
+        &self,
+        tenant_id: &str,
+        filter: &AuditLogFilter,
+        page: i32,
+        page_size: i32,
+    ) -> SecurityResult<Vec<AuditLog>> {
+        let offset = (page - 1) * page_size;
+
+        let mut query = sqlx::QueryBuilder::new(
+            "SELECT id, tenant_id, service, user_id, session_id, event_type, event_category,
+             resource_type, resource_id, action, outcome, ip_address, user_agent,
+             request_id, details, risk_score, created_at, sequence, previous_hash, hash
+             FROM audit_logs WHERE tenant_id = "
+        );
+        query.push_bind(tenant_id.to_string());
+        Self::push_filter(&mut query, filter);
 
         query.push(" ORDER BY created_at DESC LIMIT ").push_bind(page_size);
         query.push(" OFFSET ").push_bind(offset);
@@ -112,32 +197,13 @@ impl AuditRepository {
     pub async fn count_audit_logs(
         &self,
         tenant_id: &str,
-        start_date: Option<DateTime<Utc>>,
-        end_date: Option<DateTime<Utc>>,
-        event_category: Option<AuditEventCategory>,
-        user_id: Option<&str>,
-        resource_type: Option<&str>,
+        filter: &AuditLogFilter,
     ) -> SecurityResult<i64> {
         let mut query = sqlx::QueryBuilder::new(
             "SELECT COUNT(*) FROM audit_logs WHERE tenant_id = "
         );
-        query.push_bind(tenant_id);
-
-        if let Some(start) = start_date {
-            query.push(" AND created_at >= ").push_bind(start);
-        }
-        if let Some(end) = end_date {
-            query.push(" AND created_at <= ").push_bind(end);
-        }
-        if let Some(category) = event_category {
-            query.push(" AND event_category = ").push_bind(category);
-        }
-        if let Some(uid) = user_id {
-            query.push(" AND user_id = ").push_bind(uid);
-        }
-        if let Some(rtype) = resource_type {
-            query.push(" AND resource_type = ").push_bind(rtype);
-        }
+        query.push_bind(tenant_id.to_string());
+        Self::push_filter(&mut query, filter);
 
         let count: i64 = query
             .build_query_scalar()
@@ -156,11 +222,12 @@ impl AuditRepository {
         let logs = sqlx::query_as!(
             AuditLog,
             r#"
-            SELECT id, tenant_id, user_id, session_id, event_type, 
+            SELECT id, tenant_id, service, user_id, session_id, event_type, 
                    event_category as "event_category: AuditEventCategory",
                    resource_type, resource_id, action, 
                    outcome as "outcome: AuditOutcome",
-                   ip_address, user_agent, request_id, details, risk_score, created_at
+                   ip_address, user_agent, request_id, details, risk_score, created_at,
+                   sequence, previous_hash, hash
             FROM audit_logs 
             WHERE tenant_id = $1 AND created_at >= $2 AND created_at <= $3
             ORDER BY created_at DESC
@@ -175,16 +242,288 @@ impl AuditRepository {
         Ok(logs)
     }
 
+    /// A random sample of a tenant's events in `[start_date, end_date]`
+    pub async fn sample_audit_logs(
+        &self,
+        tenant_id: &str,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+        limit: i64,
+    ) -> SecurityResult<Vec<AuditLog>> {
+        let logs = sqlx::query_as!(
+            AuditLog,
+            r#"
+            SELECT id, tenant_id, service, user_id, session_id, event_type,
+                   event_category as "event_category: AuditEventCategory",
+                   resource_type, resource_id, action,
+                   outcome as "outcome: AuditOutcome",
+                   ip_address, user_agent, request_id, details, risk_score, created_at,
+                   sequence, previous_hash, hash
+            FROM audit_logs
+            WHERE tenant_id = $1 AND created_at >= $2 AND created_at <= $3
+            ORDER BY random()
+            LIMIT $4
+            "#,
+            tenant_id,
+            start_date,
+            end_date,
+            limit
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(logs)
+    }
+
+    /// Chained events after `after_sequence`, in chain order
+    pub async fn get_chain_segment(
+        &self,
+        tenant_id: &str,
+        after_sequence: i64,
+        limit: i64,
+    ) -> SecurityResult<Vec<AuditLog>> {
+        let logs = sqlx::query_as!(
+            AuditLog,
+            r#"
+            SELECT id, tenant_id, service, user_id, session_id, event_type,
+                   event_category as "event_category: AuditEventCategory",
+                   resource_type, resource_id, action,
+                   outcome as "outcome: AuditOutcome",
+                   ip_address, user_agent, request_id, details, risk_score, created_at,
+                   sequence, previous_hash, hash
+            FROM audit_logs
+            WHERE tenant_id = $1 AND sequence > $2
+            ORDER BY sequence
+            LIMIT $3
+            "#,
+            tenant_id,
+            after_sequence,
+            limit
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(logs)
+    }
+
+    pub async fn get_chain_head(&self, tenant_id: &str) -> SecurityResult<Option<(i64, String)>> {
+        let head = sqlx::query!(
+            "SELECT sequence, hash FROM audit_chain_heads WHERE tenant_id = $1",
+            tenant_id
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(head.map(|head| (head.sequence, head.hash)))
+    }
+
+    pub async fn list_audited_tenants(&self) -> SecurityResult<Vec<String>> {
+        let tenants = sqlx::query_scalar!("SELECT DISTINCT tenant_id FROM audit_logs")
+            .fetch_all(&*self.pool)
+            .await?;
+
+        Ok(tenants)
+    }
+
+    pub async fn get_retention_policy(&self, tenant_id: &str) -> SecurityResult<Option<AuditRetentionPolicy>> {
+        let policy = sqlx::query_as!(
+            AuditRetentionPolicy,
+            "SELECT tenant_id, retention_days, updated_by, created_at, updated_at
+             FROM audit_retention_policies WHERE tenant_id = $1",
+            tenant_id
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(policy)
+    }
+
+    pub async fn upsert_retention_policy(
+        &self,
+        tenant_id: &str,
+        retention_days: i32,
+        updated_by: Option<&str>,
+    ) -> SecurityResult<AuditRetentionPolicy> {
+        let policy = sqlx::query_as!(
+            AuditRetentionPolicy,
+            r#"
+            INSERT INTO audit_retention_policies (tenant_id, retention_days, updated_by)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (tenant_id) DO UPDATE SET
+                retention_days = EXCLUDED.retention_days,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = NOW()
+            RETURNING tenant_id, retention_days, updated_by, created_at, updated_at
+            "#,
+            tenant_id,
+            retention_days,
+            updated_by
+        )
+        .fetch_one(&*self.pool)
+        .await?;
+
+        Ok(policy)
+    }
+
+    pub async fn latest_retention_run(&self, tenant_id: &str) -> SecurityResult<Option<AuditRetentionRun>> {
+        let run = sqlx::query_as!(
+            AuditRetentionRun,
+            r#"
+            SELECT id, tenant_id, cutoff, deleted_count, last_deleted_sequence, last_deleted_hash, ran_at
+            FROM audit_retention_runs
+            WHERE tenant_id = $1 AND last_deleted_sequence IS NOT NULL
+            ORDER BY ran_at DESC
+            LIMIT 1
+            "#,
+            tenant_id
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(run)
+    }
+
+    /// Delete a tenant's events older than the cutoff. Only a prefix of the
+    /// chain is removed, so what is kept still verifies from the last removed
+    /// link, which is recorded with the run.
     pub async fn delete_old_logs(&self, tenant_id: &str, cutoff_date: DateTime<Utc>) -> SecurityResult<i64> {
-        let result = sqlx::query!(
-            "DELETE FROM audit_logs WHERE tenant_id = $1 AND created_at < $2",
+        let mut tx = self.pool.begin().await?;
+
+        let deleted = sqlx::query!(
+            r#"
+            DELETE FROM audit_logs
+            WHERE tenant_id = $1 AND (
+                (sequence IS NULL AND created_at < $2)
+                OR sequence < COALESCE(
+                    (SELECT MIN(sequence) FROM audit_logs WHERE tenant_id = $1 AND created_at >= $2),
+                    (SELECT MAX(sequence) + 1 FROM audit_logs WHERE tenant_id = $1)
+                )
+            )
+            RETURNING sequence, hash
+            "#,
             tenant_id,
             cutoff_date
         )
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let last_deleted = deleted
+            .iter()
+            .filter_map(|row| row.sequence.zip(row.hash.clone()))
+            .max_by_key(|(sequence, _)| *sequence);
+
+        if !deleted.is_empty() {
+            sqlx::query!(
+                r#"
+                INSERT INTO audit_retention_runs (
+                    id, tenant_id, cutoff, deleted_count, last_deleted_sequence, last_deleted_hash
+                ) VALUES ($1, $2, $3, $4, $5, $6)
+                "#,
+                Uuid::new_v4(),
+                tenant_id,
+                cutoff_date,
+                deleted.len() as i64,
+                last_deleted.as_ref().map(|(sequence, _)| *sequence),
+                last_deleted.as_ref().map(|(_, hash)| hash.clone())
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(deleted.len() as i64)
+    }
+}
+
+// Compliance Repository
+#[derive(Clone)]
+pub struct ComplianceRepository {
+    pool: Arc<PgPool>,
+}
+
+impl ComplianceRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create_report(&self, report: ComplianceReport) -> SecurityResult<ComplianceReport> {
+        sqlx::query!(
+            r#"
+            INSERT INTO compliance_reports (
+                id, tenant_id, report_type, period_start, period_end, status, findings,
+                recommendations, risk_level, generated_by, created_at, updated_at,
+                scope, template_version, compliance_score, controls, evidence
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)
+            "#,
+            report.id,
+            report.tenant_id,
+            report.report_type.clone() as ComplianceReportType,
+            report.period_start,
+            report.period_end,
+            report.status.clone() as ComplianceStatus,
+            report.findings,
+            report.recommendations,
+            report.risk_level.clone() as RiskLevel,
+            report.generated_by,
+            report.created_at,
+            report.updated_at,
+            report.scope,
+            report.template_version,
+            report.compliance_score,
+            report.controls,
+            report.evidence
+        )
         .execute(&*self.pool)
         .await?;
 
-        Ok(result.rows_affected() as i64)
+        Ok(report)
+    }
+
+    pub async fn get_report(&self, tenant_id: &str, report_id: Uuid) -> SecurityResult<Option<ComplianceReport>> {
+        let report = sqlx::query_as!(
+            ComplianceReport,
+            r#"
+            SELECT id, tenant_id, report_type as "report_type: ComplianceReportType",
+                   period_start, period_end, status as "status: ComplianceStatus",
+                   findings, recommendations, risk_level as "risk_level: RiskLevel",
+                   generated_by, created_at, updated_at, scope, template_version,
+                   compliance_score, controls, evidence
+            FROM compliance_reports WHERE tenant_id = $1 AND id = $2
+            "#,
+            tenant_id,
+            report_id
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(report)
+    }
+
+    /// Most recent reports first, without their evidence
+    pub async fn list_reports(
+        &self,
+        tenant_id: &str,
+        report_type: Option<ComplianceReportType>,
+        limit: i64,
+    ) -> SecurityResult<Vec<ComplianceReport>> {
+        let mut query = sqlx::QueryBuilder::new(
+            "SELECT id, tenant_id, report_type, period_start, period_end, status, findings,
+             recommendations, risk_level, generated_by, created_at, updated_at, scope,
+             template_version, compliance_score, controls, '[]'::jsonb AS evidence
+             FROM compliance_reports WHERE tenant_id = "
+        );
+        query.push_bind(tenant_id.to_string());
+        if let Some(report_type) = report_type {
+            query.push(" AND report_type = ").push_bind(report_type);
+        }
+        query.push(" ORDER BY created_at DESC LIMIT ").push_bind(limit);
+
+        let reports = query
+            .build_query_as::<ComplianceReport>()
+            .fetch_all(&*self.pool)
+            .await?;
+
+        Ok(reports)
     }
 }
 
@@ -1091,4 +1430,1622 @@ impl ZeroTrustRepository {
         // For now, return None to indicate unknown device
         Ok(None)
     }
-}
\ No newline at end of file
+}
+// Data Destruction Certificate Repository
+#[derive(Clone)]
+pub struct DestructionCertificateRepository {
+    pool: Arc<PgPool>,
+}
+
+impl DestructionCertificateRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create_certificate(&self, certificate: DataDestructionCertificate) -> SecurityResult<DataDestructionCertificate> {
+        sqlx::query!(
+            r#"
+            INSERT INTO data_destruction_certificates (
+                id, tenant_id, tenant_name, requested_by, soft_deleted_at, purged_at,
+                purged_services, digest, signature, signing_key_id, issued_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            "#,
+            certificate.id,
+            certificate.tenant_id,
+            certificate.tenant_name,
+            certificate.requested_by,
+            certificate.soft_deleted_at,
+            certificate.purged_at,
+            certificate.purged_services,
+            certificate.digest,
+            certificate.signature,
+            certificate.signing_key_id,
+            certificate.issued_at
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(certificate)
+    }
+
+    pub async fn get_certificate(&self, certificate_id: Uuid) -> SecurityResult<Option<DataDestructionCertificate>> {
+        let certificate = sqlx::query_as!(
+            DataDestructionCertificate,
+            r#"
+            SELECT id, tenant_id, tenant_name, requested_by, soft_deleted_at, purged_at,
+                   purged_services, digest, signature, signing_key_id, issued_at
+            FROM data_destruction_certificates WHERE id = $1
+            "#,
+            certificate_id
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(certificate)
+    }
+
+    pub async fn get_tenant_certificates(&self, tenant_id: &str) -> SecurityResult<Vec<DataDestructionCertificate>> {
+        let certificates = sqlx::query_as!(
+            DataDestructionCertificate,
+            r#"
+            SELECT id, tenant_id, tenant_name, requested_by, soft_deleted_at, purged_at,
+                   purged_services, digest, signature, signing_key_id, issued_at
+            FROM data_destruction_certificates
+            WHERE tenant_id = $1
+            ORDER BY issued_at DESC
+            "#,
+            tenant_id
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(certificates)
+    }
+}
+
+// Detection Repository
+#[derive(Clone)]
+pub struct DetectionRepository {
+    pool: Arc<PgPool>,
+}
+
+impl DetectionRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn get_checkpoint(&self, tenant_id: &str) -> SecurityResult<i64> {
+        let last_sequence = sqlx::query_scalar!(
+            "SELECT last_sequence FROM detection_checkpoints WHERE tenant_id = $1",
+            tenant_id
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(last_sequence.unwrap_or(0))
+    }
+
+    pub async fn set_checkpoint(&self, tenant_id: &str, last_sequence: i64) -> SecurityResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO detection_checkpoints (tenant_id, last_sequence) VALUES ($1, $2)
+            ON CONFLICT (tenant_id) DO UPDATE SET last_sequence = EXCLUDED.last_sequence, updated_at = NOW()
+            "#,
+            tenant_id,
+            last_sequence
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// Events of a user, or from an address, in `[since, until]`
+    pub async fn count_events(
+        &self,
+        tenant_id: &str,
+        user_id: Option<&str>,
+        ip_address: Option<&str>,
+        event_category: Option<AuditEventCategory>,
+        actions: Option<&[String]>,
+        outcome: Option<AuditOutcome>,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> SecurityResult<i64> {
+        let mut query = sqlx::QueryBuilder::new("SELECT COUNT(*) FROM audit_logs WHERE tenant_id = ");
+        query.push_bind(tenant_id.to_string());
+        query.push(" AND created_at >= ").push_bind(since);
+        query.push(" AND created_at <= ").push_bind(until);
+
+        if let Some(uid) = user_id {
+            query.push(" AND user_id = ").push_bind(uid.to_string());
+        }
+        if let Some(ip) = ip_address {
+            query.push(" AND host(ip_address) = ").push_bind(ip.to_string());
+        }
+        if let Some(category) = event_category {
+            query.push(" AND event_category = ").push_bind(category);
+        }
+        if let Some(actions) = actions {
+            query.push(" AND action = ANY(").push_bind(actions.to_vec()).push(")");
+        }
+        if let Some(outcome) = outcome {
+            query.push(" AND outcome = ").push_bind(outcome);
+        }
+
+        let count: i64 = query
+            .build_query_scalar()
+            .fetch_one(&*self.pool)
+            .await?;
+
+        Ok(count)
+    }
+
+    /// A user's matching events per hour in `[since, until)`. Hours without
+    /// any are left out.
+    pub async fn hourly_event_counts(
+        &self,
+        tenant_id: &str,
+        user_id: &str,
+        actions: &[String],
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> SecurityResult<Vec<i64>> {
+        let counts = sqlx::query_scalar!(
+            r#"
+            SELECT COUNT(*) as "count!"
+            FROM audit_logs
+            WHERE tenant_id = $1 AND user_id = $2 AND action = ANY($3)
+              AND created_at >= $4 AND created_at < $5
+            GROUP BY date_trunc('hour', created_at)
+            "#,
+            tenant_id,
+            user_id,
+            actions,
+            since,
+            until
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(counts)
+    }
+
+    /// Successful sign-ins of a user before `before`, in total and from `ip_address`
+    pub async fn prior_logins(
+        &self,
+        tenant_id: &str,
+        user_id: &str,
+        ip_address: &str,
+        since: DateTime<Utc>,
+        before: DateTime<Utc>,
+    ) -> SecurityResult<(i64, i64)> {
+        let row = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as "total!",
+                   COUNT(*) FILTER (WHERE host(ip_address) = $3) as "from_address!"
+            FROM audit_logs
+            WHERE tenant_id = $1 AND user_id = $2 AND event_category = 'authentication'
+              AND outcome = 'success' AND ip_address IS NOT NULL
+              AND created_at >= $4 AND created_at < $5
+            "#,
+            tenant_id,
+            user_id,
+            ip_address,
+            since,
+            before
+        )
+        .fetch_one(&*self.pool)
+        .await?;
+
+        Ok((row.total, row.from_address))
+    }
+
+    pub async fn find_active_alert(&self, tenant_id: &str, dedup_key: &str) -> SecurityResult<Option<SecurityAlert>> {
+        let alert = sqlx::query_as!(
+            SecurityAlert,
+            r#"
+            SELECT id, tenant_id, rule as "rule: AlertRule", severity as "severity: SecurityEventSeverity",
+                   status as "status: AlertStatus", title, description, dedup_key, user_id, event_ids,
+                   event_count, details, first_seen_at, last_seen_at, assigned_to, assigned_at,
+                   acknowledged_by, acknowledged_at, resolved_by, resolved_at, resolution_notes,
+                   created_at, updated_at
+            FROM security_alerts
+            WHERE tenant_id = $1 AND dedup_key = $2 AND status IN ('open', 'acknowledged')
+            "#,
+            tenant_id,
+            dedup_key
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(alert)
+    }
+
+    pub async fn create_alert(&self, alert: SecurityAlert) -> SecurityResult<SecurityAlert> {
+        sqlx::query!(
+            r#"
+            INSERT INTO security_alerts (
+                id, tenant_id, rule, severity, status, title, description, dedup_key, user_id,
+                event_ids, event_count, details, first_seen_at, last_seen_at, created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+            "#,
+            alert.id,
+            alert.tenant_id,
+            alert.rule as AlertRule,
+            alert.severity.clone() as SecurityEventSeverity,
+            alert.status as AlertStatus,
+            alert.title,
+            alert.description,
+            alert.dedup_key,
+            alert.user_id,
+            alert.event_ids,
+            alert.event_count,
+            alert.details,
+            alert.first_seen_at,
+            alert.last_seen_at,
+            alert.created_at,
+            alert.updated_at
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(alert)
+    }
+
+    /// Add a matching event to an active alert. The most recent 100 event
+    /// ids are kept.
+    pub async fn record_alert_match(
+        &self,
+        alert_id: Uuid,
+        event_id: Uuid,
+        seen_at: DateTime<Utc>,
+        severity: SecurityEventSeverity,
+        details: serde_json::Value,
+    ) -> SecurityResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE security_alerts SET
+                event_ids = CASE
+                    WHEN jsonb_array_length(event_ids) >= 100 THEN (event_ids - 0) || jsonb_build_array($2::uuid)
+                    ELSE event_ids || jsonb_build_array($2::uuid)
+                END,
+                event_count = event_count + 1,
+                last_seen_at = GREATEST(last_seen_at, $3),
+                severity = $4,
+                details = $5,
+                updated_at = NOW()
+            WHERE id = $1
+            "#,
+            alert_id,
+            event_id,
+            seen_at,
+            severity as SecurityEventSeverity,
+            details
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_alert(&self, tenant_id: &str, alert_id: Uuid) -> SecurityResult<Option<SecurityAlert>> {
+        let alert = sqlx::query_as!(
+            SecurityAlert,
+            r#"
+            SELECT id, tenant_id, rule as "rule: AlertRule", severity as "severity: SecurityEventSeverity",
+                   status as "status: AlertStatus", title, description, dedup_key, user_id, event_ids,
+                   event_count, details, first_seen_at, last_seen_at, assigned_to, assigned_at,
+                   acknowledged_by, acknowledged_at, resolved_by, resolved_at, resolution_notes,
+                   created_at, updated_at
+            FROM security_alerts WHERE tenant_id = $1 AND id = $2
+            "#,
+            tenant_id,
+            alert_id
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(alert)
+    }
+
+    fn push_alert_filter(query: &mut sqlx::QueryBuilder<'_, sqlx::Postgres>, filter: &SecurityAlertFilter) {
+        if let Some(status) = filter.status {
+            query.push(" AND status = ").push_bind(status);
+        }
+        if let Some(severity) = filter.severity.clone() {
+            query.push(" AND severity = ").push_bind(severity);
+        }
+        if let Some(rule) = filter.rule {
+            query.push(" AND rule = ").push_bind(rule);
+        }
+        if let Some(assignee) = filter.assigned_to.clone() {
+            query.push(" AND assigned_to = ").push_bind(assignee);
+        }
+        if let Some(uid) = filter.user_id.clone() {
+            query.push(" AND user_id = ").push_bind(uid);
+        }
+    }
+
+    pub async fn list_alerts(
+        &self,
+        tenant_id: &str,
+        filter: &SecurityAlertFilter,
+        page: i32,
+        page_size: i32,
+    ) -> SecurityResult<Vec<SecurityAlert>> {
+        let offset = (page - 1) * page_size;
+
+        let mut query = sqlx::QueryBuilder::new(
+            "SELECT id, tenant_id, rule, severity, status, title, description, dedup_key, user_id,
+             event_ids, event_count, details, first_seen_at, last_seen_at, assigned_to, assigned_at,
+             acknowledged_by, acknowledged_at, resolved_by, resolved_at, resolution_notes,
+             created_at, updated_at FROM security_alerts WHERE tenant_id = "
+        );
+        query.push_bind(tenant_id.to_string());
+        Self::push_alert_filter(&mut query, filter);
+
+        query.push(" ORDER BY last_seen_at DESC LIMIT ").push_bind(page_size);
+        query.push(" OFFSET ").push_bind(offset);
+
+        let alerts = query
+            .build_query_as::<SecurityAlert>()
+            .fetch_all(&*self.pool)
+            .await?;
+
+        Ok(alerts)
+    }
+
+    pub async fn count_alerts(&self, tenant_id: &str, filter: &SecurityAlertFilter) -> SecurityResult<i64> {
+        let mut query = sqlx::QueryBuilder::new("SELECT COUNT(*) FROM security_alerts WHERE tenant_id = ");
+        query.push_bind(tenant_id.to_string());
+        Self::push_alert_filter(&mut query, filter);
+
+        let count: i64 = query
+            .build_query_scalar()
+            .fetch_one(&*self.pool)
+            .await?;
+
+        Ok(count)
+    }
+
+    /// Alerts first seen in `[since, until]`
+    pub async fn alert_counts(
+        &self,
+        tenant_id: &str,
+        since: DateTime<Utc>,
+        until: DateTime<Utc>,
+    ) -> SecurityResult<Vec<AlertCount>> {
+        let counts = sqlx::query_as!(
+            AlertCount,
+            r#"
+            SELECT rule as "rule: AlertRule", severity as "severity: SecurityEventSeverity",
+                   status as "status: AlertStatus", COUNT(*) as "count!"
+            FROM security_alerts
+            WHERE tenant_id = $1 AND first_seen_at >= $2 AND first_seen_at <= $3
+            GROUP BY rule, severity, status
+            "#,
+            tenant_id,
+            since,
+            until
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(counts)
+    }
+
+    pub async fn update_alert(&self, alert: SecurityAlert) -> SecurityResult<SecurityAlert> {
+        sqlx::query!(
+            r#"
+            UPDATE security_alerts SET
+                status = $2, assigned_to = $3, assigned_at = $4, acknowledged_by = $5,
+                acknowledged_at = $6, resolved_by = $7, resolved_at = $8, resolution_notes = $9,
+                updated_at = $10
+            WHERE id = $1
+            "#,
+            alert.id,
+            alert.status as AlertStatus,
+            alert.assigned_to,
+            alert.assigned_at,
+            alert.acknowledged_by,
+            alert.acknowledged_at,
+            alert.resolved_by,
+            alert.resolved_at,
+            alert.resolution_notes,
+            alert.updated_at
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(alert)
+    }
+}
+
+// Key Repository
+#[derive(Clone)]
+pub struct KeyRepository {
+    pool: Arc<PgPool>,
+}
+
+impl KeyRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create_key(&self, key: ManagedKey) -> SecurityResult<ManagedKey> {
+        sqlx::query!(
+            r#"
+            INSERT INTO managed_keys (id, purpose, owner_id, kid, status, material, rotated_by, created_at, activated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#,
+            key.id,
+            key.purpose as KeyPurpose,
+            key.owner_id,
+            key.kid,
+            key.status as KeyStatus,
+            key.material,
+            key.rotated_by,
+            key.created_at,
+            key.activated_at
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(key)
+    }
+
+    pub async fn get_key(&self, kid: &str) -> SecurityResult<Option<ManagedKey>> {
+        let key = sqlx::query_as!(
+            ManagedKey,
+            r#"
+            SELECT id, purpose as "purpose: KeyPurpose", owner_id, kid, status as "status: KeyStatus",
+                   material, rotated_by, created_at, activated_at, retiring_at, retire_after, retired_at
+            FROM managed_keys
+            WHERE kid = $1
+            "#,
+            kid
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(key)
+    }
+
+    /// The owner's key in the given status; there is at most one pending and one active
+    pub async fn find_key(
+        &self,
+        purpose: KeyPurpose,
+        owner_id: &str,
+        status: KeyStatus,
+    ) -> SecurityResult<Option<ManagedKey>> {
+        let key = sqlx::query_as!(
+            ManagedKey,
+            r#"
+            SELECT id, purpose as "purpose: KeyPurpose", owner_id, kid, status as "status: KeyStatus",
+                   material, rotated_by, created_at, activated_at, retiring_at, retire_after, retired_at
+            FROM managed_keys
+            WHERE purpose = $1 AND owner_id = $2 AND status = $3
+            ORDER BY created_at DESC
+            LIMIT 1
+            "#,
+            purpose as KeyPurpose,
+            owner_id,
+            status as KeyStatus
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(key)
+    }
+
+    /// Every version of the owner's key, newest first
+    pub async fn list_keys(&self, purpose: KeyPurpose, owner_id: &str) -> SecurityResult<Vec<ManagedKey>> {
+        let keys = sqlx::query_as!(
+            ManagedKey,
+            r#"
+            SELECT id, purpose as "purpose: KeyPurpose", owner_id, kid, status as "status: KeyStatus",
+                   material, rotated_by, created_at, activated_at, retiring_at, retire_after, retired_at
+            FROM managed_keys
+            WHERE purpose = $1 AND owner_id = $2
+            ORDER BY created_at DESC
+            "#,
+            purpose as KeyPurpose,
+            owner_id
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(keys)
+    }
+
+    /// Keys of every owner with the given purpose, optionally only those in one status
+    pub async fn list_keys_by_purpose(
+        &self,
+        purpose: KeyPurpose,
+        status: Option<KeyStatus>,
+    ) -> SecurityResult<Vec<ManagedKey>> {
+        let keys = sqlx::query_as!(
+            ManagedKey,
+            r#"
+            SELECT id, purpose as "purpose: KeyPurpose", owner_id, kid, status as "status: KeyStatus",
+                   material, rotated_by, created_at, activated_at, retiring_at, retire_after, retired_at
+            FROM managed_keys
+            WHERE purpose = $1 AND ($2::key_status IS NULL OR status = $2)
+            ORDER BY owner_id, created_at
+            "#,
+            purpose as KeyPurpose,
+            status as Option<KeyStatus>
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(keys)
+    }
+
+    /// Pending keys staged before the cutoff, ready to activate
+    pub async fn list_pending_keys(&self, staged_before: DateTime<Utc>) -> SecurityResult<Vec<ManagedKey>> {
+        let keys = sqlx::query_as!(
+            ManagedKey,
+            r#"
+            SELECT id, purpose as "purpose: KeyPurpose", owner_id, kid, status as "status: KeyStatus",
+                   material, rotated_by, created_at, activated_at, retiring_at, retire_after, retired_at
+            FROM managed_keys
+            WHERE status = 'pending' AND created_at <= $1
+            ORDER BY created_at
+            "#,
+            staged_before
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(keys)
+    }
+
+    /// Retiring keys whose overlap window has closed
+    pub async fn list_expired_keys(&self, now: DateTime<Utc>) -> SecurityResult<Vec<ManagedKey>> {
+        let keys = sqlx::query_as!(
+            ManagedKey,
+            r#"
+            SELECT id, purpose as "purpose: KeyPurpose", owner_id, kid, status as "status: KeyStatus",
+                   material, rotated_by, created_at, activated_at, retiring_at, retire_after, retired_at
+            FROM managed_keys
+            WHERE status = 'retiring' AND retire_after <= $1
+            ORDER BY retire_after
+            "#,
+            now
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(keys)
+    }
+
+    /// Make a pending key the active one. The key it replaces keeps being
+    /// accepted until `retire_after`.
+    pub async fn activate_key(&self, key_id: Uuid, retire_after: DateTime<Utc>) -> SecurityResult<ManagedKey> {
+        let mut tx = self.pool.begin().await?;
+        let now = Utc::now();
+
+        let pending = sqlx::query_as!(
+            ManagedKey,
+            r#"
+            SELECT id, purpose as "purpose: KeyPurpose", owner_id, kid, status as "status: KeyStatus",
+                   material, rotated_by, created_at, activated_at, retiring_at, retire_after, retired_at
+            FROM managed_keys
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            key_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| SecurityError::NotFound(format!("Key {} not found", key_id)))?;
+
+        // Retried activations find the work already done
+        if pending.status == KeyStatus::Active {
+            tx.commit().await?;
+            return Ok(pending);
+        }
+        if pending.status != KeyStatus::Pending {
+            return Err(SecurityError::Conflict(format!("Key {} is not pending", pending.kid)));
+        }
+
+        sqlx::query!(
+            r#"
+            UPDATE managed_keys SET status = 'retiring', retiring_at = $3, retire_after = $4
+            WHERE purpose = $1 AND owner_id = $2 AND status = 'active'
+            "#,
+            pending.purpose as KeyPurpose,
+            pending.owner_id,
+            now,
+            retire_after
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE managed_keys SET status = 'active', activated_at = $2 WHERE id = $1",
+            key_id,
+            now
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        Ok(ManagedKey {
+            status: KeyStatus::Active,
+            activated_at: Some(now),
+            ..pending
+        })
+    }
+
+    pub async fn retire_key(&self, key_id: Uuid) -> SecurityResult<()> {
+        sqlx::query!(
+            "UPDATE managed_keys SET status = 'retired', retired_at = NOW() WHERE id = $1 AND status = 'retiring'",
+            key_id
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_schedule(
+        &self,
+        purpose: KeyPurpose,
+        owner_id: &str,
+    ) -> SecurityResult<Option<KeyRotationSchedule>> {
+        let schedule = sqlx::query_as!(
+            KeyRotationSchedule,
+            r#"
+            SELECT purpose as "purpose: KeyPurpose", owner_id, rotation_days, overlap_hours, enabled,
+                   last_rotated_at, next_rotation_at, created_at, updated_at
+            FROM key_rotation_schedules
+            WHERE purpose = $1 AND owner_id = $2
+            "#,
+            purpose as KeyPurpose,
+            owner_id
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(schedule)
+    }
+
+    pub async fn list_schedules(&self) -> SecurityResult<Vec<KeyRotationSchedule>> {
+        let schedules = sqlx::query_as!(
+            KeyRotationSchedule,
+            r#"
+            SELECT purpose as "purpose: KeyPurpose", owner_id, rotation_days, overlap_hours, enabled,
+                   last_rotated_at, next_rotation_at, created_at, updated_at
+            FROM key_rotation_schedules
+            ORDER BY next_rotation_at
+            "#
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(schedules)
+    }
+
+    pub async fn list_due_schedules(&self, now: DateTime<Utc>) -> SecurityResult<Vec<KeyRotationSchedule>> {
+        let schedules = sqlx::query_as!(
+            KeyRotationSchedule,
+            r#"
+            SELECT purpose as "purpose: KeyPurpose", owner_id, rotation_days, overlap_hours, enabled,
+                   last_rotated_at, next_rotation_at, created_at, updated_at
+            FROM key_rotation_schedules
+            WHERE enabled AND next_rotation_at <= $1
+            ORDER BY next_rotation_at
+            "#,
+            now
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(schedules)
+    }
+
+    pub async fn upsert_schedule(&self, schedule: KeyRotationSchedule) -> SecurityResult<KeyRotationSchedule> {
+        sqlx::query!(
+            r#"
+            INSERT INTO key_rotation_schedules (
+                purpose, owner_id, rotation_days, overlap_hours, enabled, last_rotated_at,
+                next_rotation_at, created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (purpose, owner_id) DO UPDATE SET
+                rotation_days = EXCLUDED.rotation_days,
+                overlap_hours = EXCLUDED.overlap_hours,
+                enabled = EXCLUDED.enabled,
+                next_rotation_at = EXCLUDED.next_rotation_at,
+                updated_at = EXCLUDED.updated_at
+            "#,
+            schedule.purpose as KeyPurpose,
+            schedule.owner_id,
+            schedule.rotation_days,
+            schedule.overlap_hours,
+            schedule.enabled,
+            schedule.last_rotated_at,
+            schedule.next_rotation_at,
+            schedule.created_at,
+            schedule.updated_at
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(schedule)
+    }
+
+    pub async fn mark_schedule_rotated(
+        &self,
+        purpose: KeyPurpose,
+        owner_id: &str,
+        rotated_at: DateTime<Utc>,
+        next_rotation_at: DateTime<Utc>,
+    ) -> SecurityResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE key_rotation_schedules SET last_rotated_at = $3, next_rotation_at = $4, updated_at = NOW()
+            WHERE purpose = $1 AND owner_id = $2
+            "#,
+            purpose as KeyPurpose,
+            owner_id,
+            rotated_at,
+            next_rotation_at
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+// DLP Repository
+#[derive(Clone)]
+pub struct DlpRepository {
+    pool: Arc<PgPool>,
+}
+
+impl DlpRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn get_policy(&self, tenant_id: &str) -> SecurityResult<Option<DlpPolicyRecord>> {
+        let policy = sqlx::query_as!(
+            DlpPolicyRecord,
+            "SELECT tenant_id, enabled, rules, updated_by, created_at, updated_at
+             FROM dlp_policies WHERE tenant_id = $1",
+            tenant_id
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(policy)
+    }
+
+    pub async fn upsert_policy(
+        &self,
+        tenant_id: &str,
+        enabled: bool,
+        rules: serde_json::Value,
+        updated_by: Option<&str>,
+    ) -> SecurityResult<DlpPolicyRecord> {
+        let policy = sqlx::query_as!(
+            DlpPolicyRecord,
+            r#"
+            INSERT INTO dlp_policies (tenant_id, enabled, rules, updated_by)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (tenant_id) DO UPDATE SET
+                enabled = EXCLUDED.enabled,
+                rules = EXCLUDED.rules,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = NOW()
+            RETURNING tenant_id, enabled, rules, updated_by, created_at, updated_at
+            "#,
+            tenant_id,
+            enabled,
+            rules,
+            updated_by
+        )
+        .fetch_one(&*self.pool)
+        .await?;
+
+        Ok(policy)
+    }
+}
+
+// Network Policy Repository
+#[derive(Clone)]
+pub struct NetworkPolicyRepository {
+    pool: Arc<PgPool>,
+}
+
+impl NetworkPolicyRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn get_policy(&self, tenant_id: &str) -> SecurityResult<Option<NetworkPolicyRecord>> {
+        let policy = sqlx::query_as!(
+            NetworkPolicyRecord,
+            "SELECT tenant_id, enabled, allowed_cidrs, allowed_countries, blocked_countries, block_tor, block_vpn,
+                    updated_by, created_at, updated_at
+             FROM tenant_network_policies WHERE tenant_id = $1",
+            tenant_id
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(policy)
+    }
+
+    pub async fn upsert_policy(&self, record: &NetworkPolicyRecord) -> SecurityResult<NetworkPolicyRecord> {
+        let policy = sqlx::query_as!(
+            NetworkPolicyRecord,
+            r#"
+            INSERT INTO tenant_network_policies
+                (tenant_id, enabled, allowed_cidrs, allowed_countries, blocked_countries, block_tor, block_vpn, updated_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (tenant_id) DO UPDATE SET
+                enabled = EXCLUDED.enabled,
+                allowed_cidrs = EXCLUDED.allowed_cidrs,
+                allowed_countries = EXCLUDED.allowed_countries,
+                blocked_countries = EXCLUDED.blocked_countries,
+                block_tor = EXCLUDED.block_tor,
+                block_vpn = EXCLUDED.block_vpn,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = NOW()
+            RETURNING tenant_id, enabled, allowed_cidrs, allowed_countries, blocked_countries, block_tor, block_vpn,
+                      updated_by, created_at, updated_at
+            "#,
+            record.tenant_id,
+            record.enabled,
+            &record.allowed_cidrs,
+            &record.allowed_countries,
+            &record.blocked_countries,
+            record.block_tor,
+            record.block_vpn,
+            record.updated_by
+        )
+        .fetch_one(&*self.pool)
+        .await?;
+
+        Ok(policy)
+    }
+
+    /// Every anonymizer range as (cidr, category)
+    pub async fn list_anonymizer_ranges(&self) -> SecurityResult<Vec<(String, String)>> {
+        let rows = sqlx::query!("SELECT cidr, category FROM anonymizer_ranges ORDER BY category, cidr")
+            .fetch_all(&*self.pool)
+            .await?;
+
+        Ok(rows.into_iter().map(|row| (row.cidr, row.category)).collect())
+    }
+
+    /// Swap a category's ranges for a feed's latest list in one transaction
+    pub async fn replace_anonymizer_ranges(
+        &self,
+        category: &str,
+        ranges: &[String],
+        source: &str,
+    ) -> SecurityResult<AnonymizerRangeSummary> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query!("DELETE FROM anonymizer_ranges WHERE category = $1", category)
+            .execute(&mut *tx)
+            .await?;
+
+        let inserted = sqlx::query!(
+            r#"
+            INSERT INTO anonymizer_ranges (cidr, category, source)
+            SELECT DISTINCT cidr, $2, $3 FROM UNNEST($1::TEXT[]) AS cidr
+            "#,
+            ranges,
+            category,
+            source
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        tx.commit().await?;
+
+        Ok(AnonymizerRangeSummary {
+            category: category.to_string(),
+            range_count: inserted as i64,
+            source: source.to_string(),
+            updated_at: Utc::now(),
+        })
+    }
+}
+
+// Access Review Repository
+pub struct AccessReviewRepository {
+    pool: Arc<PgPool>,
+}
+
+impl AccessReviewRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    /// Store a campaign and its review items together. Fails with a conflict
+    /// when the tenant already has an open campaign.
+    pub async fn create_campaign(
+        &self,
+        campaign: &AccessReviewCampaign,
+        items: &[AccessReviewItem],
+    ) -> SecurityResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        let inserted = sqlx::query!(
+            r#"
+            INSERT INTO access_review_campaigns (id, tenant_id, name, status, deadline, auto_revoke, created_by, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            ON CONFLICT (tenant_id) WHERE status = 'open' DO NOTHING
+            "#,
+            campaign.id,
+            campaign.tenant_id,
+            campaign.name,
+            campaign.status as AccessReviewStatus,
+            campaign.deadline,
+            campaign.auto_revoke,
+            campaign.created_by,
+            campaign.created_at
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+
+        if inserted == 0 {
+            return Err(SecurityError::Conflict(format!(
+                "Tenant {} already has an open access review",
+                campaign.tenant_id
+            )));
+        }
+
+        for item in items {
+            sqlx::query!(
+                r#"
+                INSERT INTO access_review_items
+                    (id, campaign_id, tenant_id, subject_type, subject_id, subject_name, entitlement, details, decision)
+                VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+                "#,
+                item.id,
+                item.campaign_id,
+                item.tenant_id,
+                item.subject_type as AccessReviewSubject,
+                item.subject_id,
+                item.subject_name,
+                item.entitlement,
+                item.details,
+                item.decision as AccessReviewDecision
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_campaign(&self, tenant_id: &str, campaign_id: Uuid) -> SecurityResult<Option<AccessReviewCampaign>> {
+        let campaign = sqlx::query_as!(
+            AccessReviewCampaign,
+            r#"
+            SELECT id, tenant_id, name, status as "status: AccessReviewStatus", deadline, auto_revoke, created_by,
+                   created_at, completed_at, evidence, evidence_hash
+            FROM access_review_campaigns
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+            tenant_id,
+            campaign_id
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(campaign)
+    }
+
+    /// The tenant's campaigns, newest first
+    pub async fn list_campaigns(&self, tenant_id: &str, limit: i64) -> SecurityResult<Vec<AccessReviewCampaign>> {
+        let campaigns = sqlx::query_as!(
+            AccessReviewCampaign,
+            r#"
+            SELECT id, tenant_id, name, status as "status: AccessReviewStatus", deadline, auto_revoke, created_by,
+                   created_at, completed_at, evidence, evidence_hash
+            FROM access_review_campaigns
+            WHERE tenant_id = $1
+            ORDER BY created_at DESC
+            LIMIT $2
+            "#,
+            tenant_id,
+            limit
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(campaigns)
+    }
+
+    /// Campaigns completed within the period, oldest first
+    pub async fn list_completed_campaigns(
+        &self,
+        tenant_id: &str,
+        start: DateTime<Utc>,
+        end: DateTime<Utc>,
+    ) -> SecurityResult<Vec<AccessReviewCampaign>> {
+        let campaigns = sqlx::query_as!(
+            AccessReviewCampaign,
+            r#"
+            SELECT id, tenant_id, name, status as "status: AccessReviewStatus", deadline, auto_revoke, created_by,
+                   created_at, completed_at, evidence, evidence_hash
+            FROM access_review_campaigns
+            WHERE tenant_id = $1 AND status = 'completed' AND completed_at >= $2 AND completed_at < $3
+            ORDER BY completed_at
+            "#,
+            tenant_id,
+            start,
+            end
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(campaigns)
+    }
+
+    /// Open campaigns whose deadline has passed
+    pub async fn list_expired_campaigns(&self, now: DateTime<Utc>) -> SecurityResult<Vec<AccessReviewCampaign>> {
+        let campaigns = sqlx::query_as!(
+            AccessReviewCampaign,
+            r#"
+            SELECT id, tenant_id, name, status as "status: AccessReviewStatus", deadline, auto_revoke, created_by,
+                   created_at, completed_at, evidence, evidence_hash
+            FROM access_review_campaigns
+            WHERE status = 'open' AND deadline <= $1
+            ORDER BY deadline
+            "#,
+            now
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(campaigns)
+    }
+
+    pub async fn list_items(
+        &self,
+        campaign_id: Uuid,
+        decision: Option<AccessReviewDecision>,
+    ) -> SecurityResult<Vec<AccessReviewItem>> {
+        let items = sqlx::query_as!(
+            AccessReviewItem,
+            r#"
+            SELECT id, campaign_id, tenant_id, subject_type as "subject_type: AccessReviewSubject", subject_id,
+                   subject_name, entitlement, details, decision as "decision: AccessReviewDecision", decided_by,
+                   decided_at, comment, revoked_at, auto_revoked, revocation_error
+            FROM access_review_items
+            WHERE campaign_id = $1 AND ($2::access_review_decision IS NULL OR decision = $2)
+            ORDER BY subject_type, subject_name, entitlement
+            "#,
+            campaign_id,
+            decision as Option<AccessReviewDecision>
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(items)
+    }
+
+    /// Record a reviewer's decision on an item still pending. Returns None
+    /// when the item is not in the campaign or has already been decided.
+    pub async fn decide_item(
+        &self,
+        campaign_id: Uuid,
+        item_id: Uuid,
+        decision: AccessReviewDecision,
+        decided_by: &str,
+        comment: Option<&str>,
+    ) -> SecurityResult<Option<AccessReviewItem>> {
+        let item = sqlx::query_as!(
+            AccessReviewItem,
+            r#"
+            UPDATE access_review_items
+            SET decision = $3, decided_by = $4, decided_at = NOW(), comment = $5
+            WHERE campaign_id = $1 AND id = $2 AND decision = 'pending'
+            RETURNING id, campaign_id, tenant_id, subject_type as "subject_type: AccessReviewSubject", subject_id,
+                      subject_name, entitlement, details, decision as "decision: AccessReviewDecision", decided_by,
+                      decided_at, comment, revoked_at, auto_revoked, revocation_error
+            "#,
+            campaign_id,
+            item_id,
+            decision as AccessReviewDecision,
+            decided_by,
+            comment
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(item)
+    }
+
+    /// Record the outcome of removing an item's access. A failed revocation
+    /// keeps its error so it can be followed up by hand.
+    pub async fn record_revocation(
+        &self,
+        item_id: Uuid,
+        auto_revoked: bool,
+        decided_by: &str,
+        error: Option<&str>,
+    ) -> SecurityResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE access_review_items
+            SET decision = 'revoked',
+                decided_by = COALESCE(decided_by, $3),
+                decided_at = COALESCE(decided_at, NOW()),
+                auto_revoked = $2,
+                revoked_at = CASE WHEN $4::TEXT IS NULL THEN NOW() ELSE NULL END,
+                revocation_error = $4
+            WHERE id = $1
+            "#,
+            item_id,
+            auto_revoked,
+            decided_by,
+            error
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    /// (total, pending, approved, revoked, auto_revoked, revocation_failures)
+    pub async fn item_counts(&self, campaign_id: Uuid) -> SecurityResult<(i64, i64, i64, i64, i64, i64)> {
+        let row = sqlx::query!(
+            r#"
+            SELECT COUNT(*) as "total!",
+                   COUNT(*) FILTER (WHERE decision = 'pending') as "pending!",
+                   COUNT(*) FILTER (WHERE decision = 'approved') as "approved!",
+                   COUNT(*) FILTER (WHERE decision = 'revoked') as "revoked!",
+                   COUNT(*) FILTER (WHERE auto_revoked) as "auto_revoked!",
+                   COUNT(*) FILTER (WHERE revocation_error IS NOT NULL) as "revocation_failures!"
+            FROM access_review_items
+            WHERE campaign_id = $1
+            "#,
+            campaign_id
+        )
+        .fetch_one(&*self.pool)
+        .await?;
+
+        Ok((row.total, row.pending, row.approved, row.revoked, row.auto_revoked, row.revocation_failures))
+    }
+
+    /// Close an open campaign and archive its evidence
+    pub async fn complete_campaign(
+        &self,
+        campaign_id: Uuid,
+        evidence: &serde_json::Value,
+        evidence_hash: &str,
+    ) -> SecurityResult<AccessReviewCampaign> {
+        let campaign = sqlx::query_as!(
+            AccessReviewCampaign,
+            r#"
+            UPDATE access_review_campaigns
+            SET status = 'completed', completed_at = NOW(), evidence = $2, evidence_hash = $3
+            WHERE id = $1 AND status = 'open'
+            RETURNING id, tenant_id, name, status as "status: AccessReviewStatus", deadline, auto_revoke, created_by,
+                      created_at, completed_at, evidence, evidence_hash
+            "#,
+            campaign_id,
+            evidence,
+            evidence_hash
+        )
+        .fetch_optional(&*self.pool)
+        .await?
+        .ok_or_else(|| SecurityError::Conflict(format!("Access review {} is not open", campaign_id)))?;
+
+        Ok(campaign)
+    }
+
+    pub async fn get_schedule(&self, tenant_id: &str) -> SecurityResult<Option<AccessReviewSchedule>> {
+        let schedule = sqlx::query_as!(
+            AccessReviewSchedule,
+            r#"
+            SELECT tenant_id, interval_days, review_days, auto_revoke, enabled, last_campaign_at, next_campaign_at,
+                   created_at, updated_at
+            FROM access_review_schedules
+            WHERE tenant_id = $1
+            "#,
+            tenant_id
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(schedule)
+    }
+
+    pub async fn list_due_schedules(&self, now: DateTime<Utc>) -> SecurityResult<Vec<AccessReviewSchedule>> {
+        let schedules = sqlx::query_as!(
+            AccessReviewSchedule,
+            r#"
+            SELECT tenant_id, interval_days, review_days, auto_revoke, enabled, last_campaign_at, next_campaign_at,
+                   created_at, updated_at
+            FROM access_review_schedules
+            WHERE enabled AND next_campaign_at <= $1
+            ORDER BY next_campaign_at
+            "#,
+            now
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(schedules)
+    }
+
+    pub async fn upsert_schedule(&self, schedule: AccessReviewSchedule) -> SecurityResult<AccessReviewSchedule> {
+        sqlx::query!(
+            r#"
+            INSERT INTO access_review_schedules (
+                tenant_id, interval_days, review_days, auto_revoke, enabled, last_campaign_at,
+                next_campaign_at, created_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (tenant_id) DO UPDATE SET
+                interval_days = EXCLUDED.interval_days,
+                review_days = EXCLUDED.review_days,
+                auto_revoke = EXCLUDED.auto_revoke,
+                enabled = EXCLUDED.enabled,
+                next_campaign_at = EXCLUDED.next_campaign_at,
+                updated_at = EXCLUDED.updated_at
+            "#,
+            schedule.tenant_id,
+            schedule.interval_days,
+            schedule.review_days,
+            schedule.auto_revoke,
+            schedule.enabled,
+            schedule.last_campaign_at,
+            schedule.next_campaign_at,
+            schedule.created_at,
+            schedule.updated_at
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(schedule)
+    }
+
+    pub async fn mark_schedule_started(
+        &self,
+        tenant_id: &str,
+        started_at: DateTime<Utc>,
+        next_campaign_at: DateTime<Utc>,
+    ) -> SecurityResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE access_review_schedules SET last_campaign_at = $2, next_campaign_at = $3, updated_at = NOW()
+            WHERE tenant_id = $1
+            "#,
+            tenant_id,
+            started_at,
+            next_campaign_at
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+// Incident Repository
+pub struct IncidentRepository {
+    pool: Arc<PgPool>,
+}
+
+impl IncidentRepository {
+    pub fn new(pool: Arc<PgPool>) -> Self {
+        Self { pool }
+    }
+
+    pub async fn create_incident(&self, incident: &SecurityIncident) -> SecurityResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO security_incidents (
+                id, tenant_id, title, description, severity, status, alert_ids, opened_by, assigned_to,
+                opened_at, updated_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
+            "#,
+            incident.id,
+            incident.tenant_id,
+            incident.title,
+            incident.description,
+            incident.severity.clone() as SecurityEventSeverity,
+            incident.status as IncidentStatus,
+            &incident.alert_ids,
+            incident.opened_by,
+            incident.assigned_to,
+            incident.opened_at,
+            incident.updated_at
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_incident(&self, tenant_id: &str, incident_id: Uuid) -> SecurityResult<Option<SecurityIncident>> {
+        let incident = sqlx::query_as!(
+            SecurityIncident,
+            r#"
+            SELECT id, tenant_id, title, description, severity as "severity: SecurityEventSeverity",
+                   status as "status: IncidentStatus", alert_ids, opened_by, assigned_to, opened_at, contained_at,
+                   resolved_at, closed_at, resolution_notes, postmortem, updated_at
+            FROM security_incidents
+            WHERE tenant_id = $1 AND id = $2
+            "#,
+            tenant_id,
+            incident_id
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(incident)
+    }
+
+    /// The tenant's incidents, most recently opened first
+    pub async fn list_incidents(
+        &self,
+        tenant_id: &str,
+        filter: &IncidentFilter,
+        limit: i64,
+    ) -> SecurityResult<Vec<SecurityIncident>> {
+        let incidents = sqlx::query_as!(
+            SecurityIncident,
+            r#"
+            SELECT id, tenant_id, title, description, severity as "severity: SecurityEventSeverity",
+                   status as "status: IncidentStatus", alert_ids, opened_by, assigned_to, opened_at, contained_at,
+                   resolved_at, closed_at, resolution_notes, postmortem, updated_at
+            FROM security_incidents
+            WHERE tenant_id = $1
+              AND ($2::incident_status IS NULL OR status = $2)
+              AND ($3::security_event_severity IS NULL OR severity = $3)
+              AND ($4::VARCHAR IS NULL OR assigned_to = $4)
+            ORDER BY opened_at DESC
+            LIMIT $5
+            "#,
+            tenant_id,
+            filter.status as Option<IncidentStatus>,
+            filter.severity.clone() as Option<SecurityEventSeverity>,
+            filter.assigned_to,
+            limit
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(incidents)
+    }
+
+    /// Incidents not yet resolved that an alert is already part of
+    pub async fn find_active_incident_for_alert(
+        &self,
+        tenant_id: &str,
+        alert_id: Uuid,
+    ) -> SecurityResult<Option<Uuid>> {
+        let row = sqlx::query!(
+            r#"
+            SELECT id FROM security_incidents
+            WHERE tenant_id = $1 AND $2 = ANY(alert_ids) AND status IN ('open', 'contained')
+            LIMIT 1
+            "#,
+            tenant_id,
+            alert_id
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(row.map(|row| row.id))
+    }
+
+    pub async fn update_incident(&self, incident: SecurityIncident) -> SecurityResult<SecurityIncident> {
+        sqlx::query!(
+            r#"
+            UPDATE security_incidents SET
+                status = $2, assigned_to = $3, contained_at = $4, resolved_at = $5, closed_at = $6,
+                resolution_notes = $7, postmortem = $8, updated_at = $9
+            WHERE id = $1
+            "#,
+            incident.id,
+            incident.status as IncidentStatus,
+            incident.assigned_to,
+            incident.contained_at,
+            incident.resolved_at,
+            incident.closed_at,
+            incident.resolution_notes,
+            incident.postmortem,
+            incident.updated_at
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(incident)
+    }
+
+    pub async fn create_action(&self, action: &ContainmentAction) -> SecurityResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO incident_containment_actions (
+                id, incident_id, tenant_id, action_type, target, reason, requires_approval, status,
+                requested_by, requested_at
+            ) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            "#,
+            action.id,
+            action.incident_id,
+            action.tenant_id,
+            action.action_type as ContainmentActionType,
+            action.target,
+            action.reason,
+            action.requires_approval,
+            action.status as ContainmentActionStatus,
+            action.requested_by,
+            action.requested_at
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn get_action(&self, incident_id: Uuid, action_id: Uuid) -> SecurityResult<Option<ContainmentAction>> {
+        let action = sqlx::query_as!(
+            ContainmentAction,
+            r#"
+            SELECT id, incident_id, tenant_id, action_type as "action_type: ContainmentActionType", target, reason,
+                   requires_approval, status as "status: ContainmentActionStatus", requested_by, requested_at,
+                   decided_by, decided_at, decision_comment, executed_at, result, error
+            FROM incident_containment_actions
+            WHERE incident_id = $1 AND id = $2
+            "#,
+            incident_id,
+            action_id
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(action)
+    }
+
+    pub async fn list_actions(&self, incident_id: Uuid) -> SecurityResult<Vec<ContainmentAction>> {
+        let actions = sqlx::query_as!(
+            ContainmentAction,
+            r#"
+            SELECT id, incident_id, tenant_id, action_type as "action_type: ContainmentActionType", target, reason,
+                   requires_approval, status as "status: ContainmentActionStatus", requested_by, requested_at,
+                   decided_by, decided_at, decision_comment, executed_at, result, error
+            FROM incident_containment_actions
+            WHERE incident_id = $1
+            ORDER BY requested_at
+            "#,
+            incident_id
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(actions)
+    }
+
+    /// Approve, reject or expire an action awaiting approval. Returns None
+    /// when it is no longer awaiting approval.
+    pub async fn decide_action(
+        &self,
+        action_id: Uuid,
+        status: ContainmentActionStatus,
+        decided_by: &str,
+        comment: Option<&str>,
+    ) -> SecurityResult<Option<ContainmentAction>> {
+        let action = sqlx::query_as!(
+            ContainmentAction,
+            r#"
+            UPDATE incident_containment_actions
+            SET status = $2, decided_by = $3, decided_at = NOW(), decision_comment = $4
+            WHERE id = $1 AND status = 'pendingapproval'
+            RETURNING id, incident_id, tenant_id, action_type as "action_type: ContainmentActionType", target, reason,
+                      requires_approval, status as "status: ContainmentActionStatus", requested_by, requested_at,
+                      decided_by, decided_at, decision_comment, executed_at, result, error
+            "#,
+            action_id,
+            status as ContainmentActionStatus,
+            decided_by,
+            comment
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(action)
+    }
+
+    /// Take an approved action for execution, so only one caller runs it
+    pub async fn claim_action(&self, action_id: Uuid) -> SecurityResult<Option<ContainmentAction>> {
+        let action = sqlx::query_as!(
+            ContainmentAction,
+            r#"
+            UPDATE incident_containment_actions SET status = 'executing'
+            WHERE id = $1 AND status = 'approved'
+            RETURNING id, incident_id, tenant_id, action_type as "action_type: ContainmentActionType", target, reason,
+                      requires_approval, status as "status: ContainmentActionStatus", requested_by, requested_at,
+                      decided_by, decided_at, decision_comment, executed_at, result, error
+            "#,
+            action_id
+        )
+        .fetch_optional(&*self.pool)
+        .await?;
+
+        Ok(action)
+    }
+
+    pub async fn finish_action(
+        &self,
+        action_id: Uuid,
+        status: ContainmentActionStatus,
+        result: Option<&serde_json::Value>,
+        error: Option<&str>,
+    ) -> SecurityResult<ContainmentAction> {
+        let action = sqlx::query_as!(
+            ContainmentAction,
+            r#"
+            UPDATE incident_containment_actions SET status = $2, executed_at = NOW(), result = $3, error = $4
+            WHERE id = $1
+            RETURNING id, incident_id, tenant_id, action_type as "action_type: ContainmentActionType", target, reason,
+                      requires_approval, status as "status: ContainmentActionStatus", requested_by, requested_at,
+                      decided_by, decided_at, decision_comment, executed_at, result, error
+            "#,
+            action_id,
+            status as ContainmentActionStatus,
+            result,
+            error
+        )
+        .fetch_one(&*self.pool)
+        .await?;
+
+        Ok(action)
+    }
+
+    pub async fn add_timeline_entry(&self, entry: &IncidentTimelineEntry) -> SecurityResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO incident_timeline (id, incident_id, tenant_id, entry_type, actor, message, details, occurred_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            entry.id,
+            entry.incident_id,
+            entry.tenant_id,
+            entry.entry_type,
+            entry.actor,
+            entry.message,
+            entry.details,
+            entry.occurred_at
+        )
+        .execute(&*self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    pub async fn list_timeline(&self, incident_id: Uuid) -> SecurityResult<Vec<IncidentTimelineEntry>> {
+        let entries = sqlx::query_as!(
+            IncidentTimelineEntry,
+            r#"
+            SELECT id, incident_id, tenant_id, entry_type, actor, message, details, occurred_at
+            FROM incident_timeline
+            WHERE incident_id = $1
+            ORDER BY occurred_at
+            "#,
+            incident_id
+        )
+        .fetch_all(&*self.pool)
+        .await?;
+
+        Ok(entries)
+    }
+}
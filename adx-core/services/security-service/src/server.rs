@@ -0,0 +1,1055 @@
+use anyhow::Result;
+use axum::{
+    extract::{Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{IntoResponse, Json, Response},
+    routing::{get, post, put},
+    Router,
+};
+use chrono::{DateTime, Utc};
+use serde::Deserialize;
+use std::{sync::Arc, time::Duration};
+use tower::ServiceBuilder;
+use tower_http::{cors::CorsLayer, trace::TraceLayer};
+use tracing::{error, info};
+use uuid::Uuid;
+
+use adx_shared::dlp::DlpPolicy;
+use adx_shared::network_policy::{AnonymizerCategory, AnonymizerRange, NetworkPolicy};
+use adx_shared::signing_keys::SigningKeySet;
+use adx_shared::telemetry::trace_http_requests;
+
+use crate::{
+    access_reviews::AccessReviewService,
+    audit::AuditService,
+    compliance::{self, ComplianceService},
+    config::SecurityConfig,
+    destruction::{DestructionCertificateService, DestructionCertificateSigner},
+    detection::DetectionService,
+    dlp::DlpPolicyService,
+    encryption::EncryptionService,
+    error::{SecurityError, SecurityResult},
+    incidents::IncidentService,
+    keys::KeyManagementService,
+    network::NetworkPolicyService,
+    models::{
+        AccessReviewCampaign, AccessReviewDecision, AccessReviewItem, AccessReviewSchedule, AccessReviewSummary,
+        AttestAccessRequest, CloseAccessReviewRequest, SetAccessReviewScheduleRequest, StartAccessReviewRequest,
+        AcknowledgeAlertRequest, AlertRule, AlertStatus, AssignAlertRequest, AuditChainVerification,
+        AuditEventCategory, AuditLogFilter, AuditLogResponse, AuditOutcome, AuditRetentionPolicy,
+        AuditRetentionSettings, ComplianceReport, ComplianceReportType, ComplianceTemplate,
+        DataDestructionCertificate, DestructionCertificateVerification, IssueDestructionCertificateRequest,
+        GenerateComplianceReportRequest, IngestAuditEventsRequest, IngestAuditEventsResponse,
+        KeyLifecycle, KeyPurpose, KeyRotationSchedule, ManagedKey, RotateKeyRequest,
+        SetKeyRotationScheduleRequest, SetDlpPolicyRequest, SetNetworkPolicyRequest, ReplaceAnonymizerRangesRequest,
+        AnonymizerRangeSummary, ReopenAlertRequest, ResolveAlertRequest, SecurityAlert, SecurityAlertFilter,
+        SecurityAlertListResponse, SecurityEventSeverity, SetAuditRetentionPolicyRequest,
+        ContainmentAction, DecideContainmentRequest, IncidentDetail, IncidentFilter, IncidentNoteRequest,
+        IncidentPostmortem, IncidentStatus, IncidentTimelineEntry, OpenIncidentRequest, RequestContainmentRequest,
+        SecurityIncident, UpdateIncidentStatusRequest,
+    },
+    repositories::{
+        AccessReviewRepository, AuditRepository, ComplianceRepository, DestructionCertificateRepository, DetectionRepository, DlpRepository,
+        KeyRepository, NetworkPolicyRepository,
+        IncidentRepository, RetentionRepository,
+    },
+    retention::DataRetentionService,
+};
+
+const TENANT_HEADER: &str = "X-Tenant-ID";
+
+pub struct AppState {
+    pub config: SecurityConfig,
+    pub audit_service: Arc<AuditService>,
+    pub detection_service: Arc<DetectionService>,
+    pub compliance_service: Arc<ComplianceService>,
+    pub key_service: Arc<KeyManagementService>,
+    pub dlp_service: Arc<DlpPolicyService>,
+    pub network_service: Arc<NetworkPolicyService>,
+    pub access_review_service: Arc<AccessReviewService>,
+    pub incident_service: Arc<IncidentService>,
+    pub destruction_service: Arc<DestructionCertificateService>,
+}
+
+pub struct SecurityServer {
+    config: SecurityConfig,
+    state: Arc<AppState>,
+}
+
+impl SecurityServer {
+    pub async fn new(config: SecurityConfig) -> Result<Self> {
+        let pool = sqlx::postgres::PgPoolOptions::new()
+            .max_connections(config.database.max_connections)
+            .min_connections(config.database.min_connections)
+            .acquire_timeout(Duration::from_secs(config.database.acquire_timeout))
+            .connect(&config.database.url)
+            .await?;
+
+        sqlx::migrate!("./migrations").run(&pool).await?;
+
+        let encryption = Arc::new(
+            EncryptionService::from_config(
+                &config.encryption.master_key_id,
+                &config.encryption.algorithm,
+                config.encryption.key_rotation_days,
+            )
+            .await?,
+        );
+        let pool = Arc::new(pool);
+        let audit_repository = Arc::new(AuditRepository::new(pool.clone()));
+        let detection_repository = Arc::new(DetectionRepository::new(pool.clone()));
+        let audit_service = Arc::new(AuditService::new(
+            audit_repository.clone(),
+            encryption.clone(),
+            config.audit.batch_size as usize,
+            config.audit.encryption_enabled,
+        ));
+        let detection_service = Arc::new(DetectionService::new(
+            audit_repository.clone(),
+            detection_repository.clone(),
+            audit_service.clone(),
+            config.detection.clone(),
+        ));
+        let incident_service = Arc::new(IncidentService::new(
+            Arc::new(IncidentRepository::new(pool.clone())),
+            detection_service.clone(),
+            audit_service.clone(),
+            config.incident.clone(),
+        ));
+        let retention_service = Arc::new(DataRetentionService::new(
+            Arc::new(RetentionRepository::new(pool.clone())),
+            audit_service.clone(),
+        ));
+        let key_service = Arc::new(KeyManagementService::new(
+            Arc::new(KeyRepository::new(pool.clone())),
+            encryption.clone(),
+            audit_service.clone(),
+            config.key_rotation.clone(),
+        ));
+        // Data encrypted with rotated keys must decrypt from the first request
+        key_service.load_tenant_keys().await?;
+        let dlp_service = Arc::new(DlpPolicyService::new(
+            Arc::new(DlpRepository::new(pool.clone())),
+            audit_service.clone(),
+        ));
+        let network_service = Arc::new(NetworkPolicyService::new(
+            Arc::new(NetworkPolicyRepository::new(pool.clone())),
+            audit_service.clone(),
+        ));
+        let destruction_service = Arc::new(DestructionCertificateService::new(
+            Arc::new(DestructionCertificateRepository::new(pool.clone())),
+            audit_service.clone(),
+            Arc::new(DestructionCertificateSigner::from_secret(&config.compliance.destruction_signing_key)?),
+        ));
+        let access_review_repository = Arc::new(AccessReviewRepository::new(pool.clone()));
+        let access_review_service = Arc::new(AccessReviewService::new(
+            access_review_repository.clone(),
+            audit_service.clone(),
+            config.access_review.clone(),
+        ));
+
+        let compliance_service = Arc::new(ComplianceService::new(
+            Arc::new(ComplianceRepository::new(pool)),
+            audit_repository,
+            detection_repository,
+            access_review_repository,
+            audit_service.clone(),
+            retention_service,
+            encryption,
+            config.audit.retention_days as i32,
+            config.audit.encryption_enabled,
+        ));
+
+        let state = Arc::new(AppState {
+            config: config.clone(),
+            audit_service,
+            detection_service,
+            compliance_service,
+            key_service,
+            dlp_service,
+            network_service,
+            access_review_service,
+            incident_service,
+            destruction_service,
+        });
+
+        Ok(Self { config, state })
+    }
+
+    pub async fn run(self) -> Result<()> {
+        self.spawn_audit_flush();
+        self.spawn_audit_retention();
+        if self.config.detection.enabled {
+            self.spawn_detection();
+        }
+        if self.config.key_rotation.enabled {
+            self.spawn_key_rotation();
+        }
+        if self.config.access_review.enabled {
+            self.spawn_access_reviews();
+        }
+
+        let app = create_router(self.state.clone());
+        let listener = tokio::net::TcpListener::bind(format!("{}:{}", self.config.server.host, self.config.server.port)).await?;
+
+        info!("Security Service listening on {}:{}", self.config.server.host, self.config.server.port);
+
+        axum::serve(listener, app).await?;
+        Ok(())
+    }
+
+    /// Store queued audit events even when a batch never fills up
+    fn spawn_audit_flush(&self) {
+        let audit_service = self.state.audit_service.clone();
+        let interval = Duration::from_secs(self.config.audit.flush_interval_seconds.max(1) as u64);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                if let Err(e) = audit_service.flush_pending().await {
+                    error!(error = %e, "Failed to flush pending audit events");
+                }
+            }
+        });
+    }
+
+    fn spawn_audit_retention(&self) {
+        let audit_service = self.state.audit_service.clone();
+        let default_retention_days = self.config.audit.retention_days as i32;
+        let interval = Duration::from_secs(self.config.audit.retention_check_interval_hours.max(1) as u64 * 3600);
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match audit_service.apply_retention_policies(default_retention_days).await {
+                    Ok(deleted_count) => info!(deleted_count = deleted_count, "Applied audit retention policies"),
+                    Err(e) => error!(error = %e, "Failed to apply audit retention policies"),
+                }
+            }
+        });
+    }
+
+    fn spawn_detection(&self) {
+        let detection_service = self.state.detection_service.clone();
+        let interval = Duration::from_secs(self.config.detection.interval_seconds.max(1));
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match detection_service.run().await {
+                    Ok(0) => {}
+                    Ok(raised) => info!(raised = raised, "Detection run raised security alerts"),
+                    Err(e) => error!(error = %e, "Detection run failed"),
+                }
+            }
+        });
+    }
+
+    /// Stage scheduled rotations, activate propagated keys and retire keys
+    /// whose overlap window has closed
+    fn spawn_key_rotation(&self) {
+        let key_service = self.state.key_service.clone();
+        let interval = Duration::from_secs(self.config.key_rotation.check_interval_seconds.max(1));
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match key_service.run_due().await {
+                    Ok(summary) if summary.staged + summary.activated + summary.retired + summary.failed == 0 => {}
+                    Ok(summary) => info!(
+                        staged = summary.staged,
+                        activated = summary.activated,
+                        retired = summary.retired,
+                        failed = summary.failed,
+                        "Advanced key rotations"
+                    ),
+                    Err(e) => error!(error = %e, "Key rotation pass failed"),
+                }
+            }
+        });
+    }
+
+    /// Start scheduled access reviews and close those past their deadline
+    fn spawn_access_reviews(&self) {
+        let access_review_service = self.state.access_review_service.clone();
+        let interval = Duration::from_secs(self.config.access_review.check_interval_seconds.max(1));
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            loop {
+                ticker.tick().await;
+                match access_review_service.run_due().await {
+                    Ok(summary) if summary.started + summary.completed + summary.failed == 0 => {}
+                    Ok(summary) => info!(
+                        started = summary.started,
+                        completed = summary.completed,
+                        failed = summary.failed,
+                        "Advanced access reviews"
+                    ),
+                    Err(e) => error!(error = %e, "Access review pass failed"),
+                }
+            }
+        });
+    }
+}
+
+pub fn create_router(state: Arc<AppState>) -> Router {
+    Router::new()
+        .route("/health", get(health_check))
+        .route("/api/v1/audit/events", post(ingest_audit_events))
+        .route("/api/v1/audit/tenants/:tenant_id/events", get(list_audit_events))
+        .route("/api/v1/audit/tenants/:tenant_id/export", get(export_audit_events))
+        .route("/api/v1/audit/tenants/:tenant_id/verify", get(verify_audit_chain))
+        .route(
+            "/api/v1/audit/tenants/:tenant_id/retention",
+            get(get_audit_retention).put(set_audit_retention),
+        )
+        .route("/api/v1/security/alerts", get(list_security_alerts))
+        .route("/api/v1/security/alerts/:alert_id", get(get_security_alert))
+        .route("/api/v1/security/alerts/:alert_id/acknowledge", post(acknowledge_security_alert))
+        .route("/api/v1/security/alerts/:alert_id/assign", post(assign_security_alert))
+        .route("/api/v1/security/alerts/:alert_id/resolve", post(resolve_security_alert))
+        .route("/api/v1/security/alerts/:alert_id/reopen", post(reopen_security_alert))
+        .route("/api/v1/security/incidents", get(list_security_incidents).post(open_security_incident))
+        .route("/api/v1/security/incidents/:incident_id", get(get_security_incident))
+        .route("/api/v1/security/incidents/:incident_id/notes", post(add_incident_note))
+        .route("/api/v1/security/incidents/:incident_id/status", post(update_incident_status))
+        .route("/api/v1/security/incidents/:incident_id/postmortem", put(set_incident_postmortem))
+        .route("/api/v1/security/incidents/:incident_id/actions", post(request_containment_action))
+        .route(
+            "/api/v1/security/incidents/:incident_id/actions/:action_id/approve",
+            post(approve_containment_action),
+        )
+        .route(
+            "/api/v1/security/incidents/:incident_id/actions/:action_id/reject",
+            post(reject_containment_action),
+        )
+        .route("/api/v1/compliance/templates", get(list_compliance_templates))
+        .route("/api/v1/compliance/reports", get(list_compliance_reports).post(generate_compliance_report))
+        .route("/api/v1/compliance/reports/:report_id", get(get_compliance_report))
+        .route("/api/v1/compliance/reports/:report_id/download", get(download_compliance_report))
+        .route(
+            "/api/v1/compliance/platform/reports",
+            get(list_platform_compliance_reports).post(generate_platform_compliance_report),
+        )
+        .route("/api/v1/compliance/platform/reports/:report_id", get(get_platform_compliance_report))
+        .route("/api/v1/compliance/platform/reports/:report_id/download", get(download_platform_compliance_report))
+        .route("/api/v1/destruction-certificates", post(issue_destruction_certificate))
+        .route("/api/v1/destruction-certificates/:certificate_id", get(get_destruction_certificate))
+        .route("/api/v1/destruction-certificates/:certificate_id/verify", get(verify_destruction_certificate))
+        .route("/api/v1/keys/schedules", get(list_key_rotation_schedules))
+        .route("/api/v1/keys/:purpose/:owner_id", get(get_key_lifecycle))
+        .route("/api/v1/keys/:purpose/:owner_id/keyset", get(get_signing_key_set))
+        .route("/api/v1/keys/:purpose/:owner_id/rotate", post(rotate_key))
+        .route("/api/v1/keys/:purpose/:owner_id/schedule", put(set_key_rotation_schedule))
+        .route("/api/v1/dlp/tenants/:tenant_id/policy", get(get_dlp_policy).put(set_dlp_policy))
+        .route("/api/v1/dlp/tenants/:tenant_id/incidents", get(list_dlp_incidents))
+        .route("/api/v1/network/tenants/:tenant_id/policy", get(get_network_policy).put(set_network_policy))
+        .route("/api/v1/network/tenants/:tenant_id/denials", get(list_network_denials))
+        .route("/api/v1/network/anonymizer-ranges", get(list_anonymizer_ranges))
+        .route("/api/v1/network/anonymizer-ranges/:category", put(replace_anonymizer_ranges))
+        .route(
+            "/api/v1/access-reviews/tenants/:tenant_id/campaigns",
+            get(list_access_reviews).post(start_access_review),
+        )
+        .route("/api/v1/access-reviews/tenants/:tenant_id/campaigns/:campaign_id", get(get_access_review))
+        .route("/api/v1/access-reviews/tenants/:tenant_id/campaigns/:campaign_id/items", get(list_access_review_items))
+        .route(
+            "/api/v1/access-reviews/tenants/:tenant_id/campaigns/:campaign_id/attestations",
+            post(attest_access_review),
+        )
+        .route("/api/v1/access-reviews/tenants/:tenant_id/campaigns/:campaign_id/close", post(close_access_review))
+        .route(
+            "/api/v1/access-reviews/tenants/:tenant_id/campaigns/:campaign_id/evidence",
+            get(get_access_review_evidence),
+        )
+        .route(
+            "/api/v1/access-reviews/tenants/:tenant_id/schedule",
+            get(get_access_review_schedule).put(set_access_review_schedule),
+        )
+        .layer(
+            ServiceBuilder::new()
+                .layer(axum::middleware::from_fn(trace_http_requests))
+                .layer(TraceLayer::new_for_http())
+                .layer(CorsLayer::permissive())
+        )
+        .with_state(state)
+}
+
+async fn health_check() -> Json<serde_json::Value> {
+    Json(serde_json::json!({
+        "status": "healthy",
+        "service": "security-service",
+        "timestamp": Utc::now(),
+    }))
+}
+
+fn tenant_from_headers(headers: &HeaderMap) -> SecurityResult<String> {
+    headers
+        .get(TENANT_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .filter(|tenant_id| !tenant_id.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| SecurityError::Authorization(format!("{} header is required", TENANT_HEADER)))
+}
+
+/// Callers only read the audit log of the tenant they are acting for
+fn require_tenant(headers: &HeaderMap, tenant_id: &str) -> SecurityResult<()> {
+    if tenant_from_headers(headers)? != tenant_id {
+        return Err(SecurityError::Authorization("Audit log belongs to another tenant".to_string()));
+    }
+    Ok(())
+}
+
+async fn ingest_audit_events(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<IngestAuditEventsRequest>,
+) -> SecurityResult<(StatusCode, Json<IngestAuditEventsResponse>)> {
+    let response = state.audit_service.ingest_events(request.events).await?;
+    Ok((StatusCode::ACCEPTED, Json(response)))
+}
+
+#[derive(Debug, Deserialize)]
+struct AuditLogQuery {
+    start_date: Option<DateTime<Utc>>,
+    end_date: Option<DateTime<Utc>>,
+    event_category: Option<AuditEventCategory>,
+    user_id: Option<String>,
+    resource_type: Option<String>,
+    resource_id: Option<String>,
+    service: Option<String>,
+    action: Option<String>,
+    outcome: Option<AuditOutcome>,
+    page: Option<i32>,
+    page_size: Option<i32>,
+}
+
+async fn list_audit_events(
+    State(state): State<Arc<AppState>>,
+    Path(tenant_id): Path<String>,
+    headers: HeaderMap,
+    Query(query): Query<AuditLogQuery>,
+) -> SecurityResult<Json<AuditLogResponse>> {
+    require_tenant(&headers, &tenant_id)?;
+
+    let filter = AuditLogFilter {
+        start_date: query.start_date,
+        end_date: query.end_date,
+        event_category: query.event_category,
+        user_id: query.user_id,
+        resource_type: query.resource_type,
+        resource_id: query.resource_id,
+        service: query.service,
+        action: query.action,
+        outcome: query.outcome,
+    };
+    let page = query.page.unwrap_or(1).max(1);
+    let page_size = query.page_size.unwrap_or(50).clamp(1, 500);
+
+    let response = state.audit_service.get_audit_logs(&tenant_id, &filter, page, page_size).await?;
+    Ok(Json(response))
+}
+
+#[derive(Debug, Deserialize)]
+struct AuditExportQuery {
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+    format: Option<String>,
+}
+
+async fn export_audit_events(
+    State(state): State<Arc<AppState>>,
+    Path(tenant_id): Path<String>,
+    headers: HeaderMap,
+    Query(query): Query<AuditExportQuery>,
+) -> SecurityResult<Response> {
+    require_tenant(&headers, &tenant_id)?;
+
+    let format = query.format.unwrap_or_else(|| "json".to_string());
+    let content = state.audit_service
+        .export_audit_logs(&tenant_id, query.start_date, query.end_date, &format)
+        .await?;
+    let content_type = if format == "csv" { "text/csv" } else { "application/json" };
+
+    Ok(([(header::CONTENT_TYPE, content_type)], content).into_response())
+}
+
+async fn verify_audit_chain(
+    State(state): State<Arc<AppState>>,
+    Path(tenant_id): Path<String>,
+    headers: HeaderMap,
+) -> SecurityResult<Json<AuditChainVerification>> {
+    require_tenant(&headers, &tenant_id)?;
+    Ok(Json(state.audit_service.verify_chain(&tenant_id).await?))
+}
+
+async fn get_audit_retention(
+    State(state): State<Arc<AppState>>,
+    Path(tenant_id): Path<String>,
+    headers: HeaderMap,
+) -> SecurityResult<Json<AuditRetentionSettings>> {
+    require_tenant(&headers, &tenant_id)?;
+
+    let settings = state.audit_service
+        .get_retention_settings(&tenant_id, state.config.audit.retention_days as i32)
+        .await?;
+    Ok(Json(settings))
+}
+
+async fn set_audit_retention(
+    State(state): State<Arc<AppState>>,
+    Path(tenant_id): Path<String>,
+    headers: HeaderMap,
+    Json(request): Json<SetAuditRetentionPolicyRequest>,
+) -> SecurityResult<Json<AuditRetentionPolicy>> {
+    require_tenant(&headers, &tenant_id)?;
+    Ok(Json(state.audit_service.set_retention_policy(&tenant_id, request).await?))
+}
+
+#[derive(Debug, Deserialize)]
+struct SecurityAlertQuery {
+    status: Option<AlertStatus>,
+    severity: Option<SecurityEventSeverity>,
+    rule: Option<AlertRule>,
+    assigned_to: Option<String>,
+    user_id: Option<String>,
+    page: Option<i32>,
+    page_size: Option<i32>,
+}
+
+async fn list_security_alerts(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<SecurityAlertQuery>,
+) -> SecurityResult<Json<SecurityAlertListResponse>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+
+    let filter = SecurityAlertFilter {
+        status: query.status,
+        severity: query.severity,
+        rule: query.rule,
+        assigned_to: query.assigned_to,
+        user_id: query.user_id,
+    };
+    let page = query.page.unwrap_or(1).max(1);
+    let page_size = query.page_size.unwrap_or(50).clamp(1, 500);
+
+    let response = state.detection_service.list_alerts(&tenant_id, &filter, page, page_size).await?;
+    Ok(Json(response))
+}
+
+async fn get_security_alert(
+    State(state): State<Arc<AppState>>,
+    Path(alert_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> SecurityResult<Json<SecurityAlert>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Json(state.detection_service.get_alert(&tenant_id, alert_id).await?))
+}
+
+async fn acknowledge_security_alert(
+    State(state): State<Arc<AppState>>,
+    Path(alert_id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(request): Json<AcknowledgeAlertRequest>,
+) -> SecurityResult<Json<SecurityAlert>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Json(state.detection_service.acknowledge_alert(&tenant_id, alert_id, request).await?))
+}
+
+async fn assign_security_alert(
+    State(state): State<Arc<AppState>>,
+    Path(alert_id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(request): Json<AssignAlertRequest>,
+) -> SecurityResult<Json<SecurityAlert>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Json(state.detection_service.assign_alert(&tenant_id, alert_id, request).await?))
+}
+
+async fn resolve_security_alert(
+    State(state): State<Arc<AppState>>,
+    Path(alert_id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(request): Json<ResolveAlertRequest>,
+) -> SecurityResult<Json<SecurityAlert>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Json(state.detection_service.resolve_alert(&tenant_id, alert_id, request).await?))
+}
+
+async fn reopen_security_alert(
+    State(state): State<Arc<AppState>>,
+    Path(alert_id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(request): Json<ReopenAlertRequest>,
+) -> SecurityResult<Json<SecurityAlert>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Json(state.detection_service.reopen_alert(&tenant_id, alert_id, request).await?))
+}
+
+#[derive(Debug, Deserialize)]
+struct SecurityIncidentQuery {
+    status: Option<IncidentStatus>,
+    severity: Option<SecurityEventSeverity>,
+    assigned_to: Option<String>,
+}
+
+async fn list_security_incidents(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<SecurityIncidentQuery>,
+) -> SecurityResult<Json<Vec<SecurityIncident>>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+
+    let filter = IncidentFilter {
+        status: query.status,
+        severity: query.severity,
+        assigned_to: query.assigned_to,
+    };
+    Ok(Json(state.incident_service.list_incidents(&tenant_id, &filter).await?))
+}
+
+async fn open_security_incident(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<OpenIncidentRequest>,
+) -> SecurityResult<(StatusCode, Json<SecurityIncident>)> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let incident = state.incident_service.open_incident(&tenant_id, request).await?;
+    Ok((StatusCode::CREATED, Json(incident)))
+}
+
+async fn get_security_incident(
+    State(state): State<Arc<AppState>>,
+    Path(incident_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> SecurityResult<Json<IncidentDetail>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Json(state.incident_service.get_detail(&tenant_id, incident_id).await?))
+}
+
+async fn add_incident_note(
+    State(state): State<Arc<AppState>>,
+    Path(incident_id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(request): Json<IncidentNoteRequest>,
+) -> SecurityResult<(StatusCode, Json<IncidentTimelineEntry>)> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let entry = state.incident_service.add_note(&tenant_id, incident_id, request).await?;
+    Ok((StatusCode::CREATED, Json(entry)))
+}
+
+async fn update_incident_status(
+    State(state): State<Arc<AppState>>,
+    Path(incident_id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(request): Json<UpdateIncidentStatusRequest>,
+) -> SecurityResult<Json<SecurityIncident>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Json(state.incident_service.update_status(&tenant_id, incident_id, request).await?))
+}
+
+async fn set_incident_postmortem(
+    State(state): State<Arc<AppState>>,
+    Path(incident_id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(postmortem): Json<IncidentPostmortem>,
+) -> SecurityResult<Json<SecurityIncident>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Json(state.incident_service.set_postmortem(&tenant_id, incident_id, postmortem).await?))
+}
+
+async fn request_containment_action(
+    State(state): State<Arc<AppState>>,
+    Path(incident_id): Path<Uuid>,
+    headers: HeaderMap,
+    Json(request): Json<RequestContainmentRequest>,
+) -> SecurityResult<(StatusCode, Json<ContainmentAction>)> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let action = state.incident_service.request_action(&tenant_id, incident_id, request).await?;
+    Ok((StatusCode::CREATED, Json(action)))
+}
+
+async fn approve_containment_action(
+    State(state): State<Arc<AppState>>,
+    Path((incident_id, action_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+    Json(request): Json<DecideContainmentRequest>,
+) -> SecurityResult<Json<ContainmentAction>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Json(state.incident_service.approve_action(&tenant_id, incident_id, action_id, request).await?))
+}
+
+async fn reject_containment_action(
+    State(state): State<Arc<AppState>>,
+    Path((incident_id, action_id)): Path<(Uuid, Uuid)>,
+    headers: HeaderMap,
+    Json(request): Json<DecideContainmentRequest>,
+) -> SecurityResult<Json<ContainmentAction>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Json(state.incident_service.reject_action(&tenant_id, incident_id, action_id, request).await?))
+}
+
+async fn list_compliance_templates() -> Json<Vec<ComplianceTemplate>> {
+    Json(compliance::templates())
+}
+
+#[derive(Debug, Deserialize)]
+struct ComplianceReportQuery {
+    report_type: Option<ComplianceReportType>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ReportDownloadQuery {
+    format: Option<String>,
+}
+
+fn report_download(report_id: Uuid, format: &str, content: Vec<u8>) -> Response {
+    let (content_type, extension) = if format == "csv" { ("text/csv", "csv") } else { ("application/json", "json") };
+    let disposition = format!("attachment; filename=\"compliance-report-{}.{}\"", report_id, extension);
+
+    (
+        [(header::CONTENT_TYPE, content_type.to_string()), (header::CONTENT_DISPOSITION, disposition)],
+        content,
+    ).into_response()
+}
+
+async fn generate_compliance_report(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Json(request): Json<GenerateComplianceReportRequest>,
+) -> SecurityResult<(StatusCode, Json<ComplianceReport>)> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let report = state.compliance_service.generate_report(Some(&tenant_id), request).await?;
+    Ok((StatusCode::CREATED, Json(report)))
+}
+
+async fn list_compliance_reports(
+    State(state): State<Arc<AppState>>,
+    headers: HeaderMap,
+    Query(query): Query<ComplianceReportQuery>,
+) -> SecurityResult<Json<Vec<ComplianceReport>>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Json(state.compliance_service.list_reports(Some(&tenant_id), query.report_type).await?))
+}
+
+async fn get_compliance_report(
+    State(state): State<Arc<AppState>>,
+    Path(report_id): Path<Uuid>,
+    headers: HeaderMap,
+) -> SecurityResult<Json<ComplianceReport>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Json(state.compliance_service.get_report(Some(&tenant_id), report_id).await?))
+}
+
+async fn download_compliance_report(
+    State(state): State<Arc<AppState>>,
+    Path(report_id): Path<Uuid>,
+    headers: HeaderMap,
+    Query(query): Query<ReportDownloadQuery>,
+) -> SecurityResult<Response> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let format = query.format.unwrap_or_else(|| "json".to_string());
+    let content = state.compliance_service.export_report(Some(&tenant_id), report_id, &format).await?;
+    Ok(report_download(report_id, &format, content))
+}
+
+// Platform reports cover every tenant and are for platform operators only;
+// the API gateway does not route tenant traffic to them.
+async fn generate_platform_compliance_report(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<GenerateComplianceReportRequest>,
+) -> SecurityResult<(StatusCode, Json<ComplianceReport>)> {
+    let report = state.compliance_service.generate_report(None, request).await?;
+    Ok((StatusCode::CREATED, Json(report)))
+}
+
+/// Called by tenant-service once every service has purged a deleted tenant;
+/// reissuing with the same certificate id returns the stored certificate
+async fn issue_destruction_certificate(
+    State(state): State<Arc<AppState>>,
+    Json(request): Json<IssueDestructionCertificateRequest>,
+) -> SecurityResult<(StatusCode, Json<DataDestructionCertificate>)> {
+    let certificate = state.destruction_service.issue_certificate(request).await?;
+    Ok((StatusCode::CREATED, Json(certificate)))
+}
+
+async fn get_destruction_certificate(
+    State(state): State<Arc<AppState>>,
+    Path(certificate_id): Path<Uuid>,
+) -> SecurityResult<Json<DataDestructionCertificate>> {
+    Ok(Json(state.destruction_service.get_certificate(certificate_id).await?))
+}
+
+async fn verify_destruction_certificate(
+    State(state): State<Arc<AppState>>,
+    Path(certificate_id): Path<Uuid>,
+) -> SecurityResult<Json<DestructionCertificateVerification>> {
+    Ok(Json(state.destruction_service.verify_certificate(certificate_id).await?))
+}
+
+async fn list_platform_compliance_reports(
+    State(state): State<Arc<AppState>>,
+    Query(query): Query<ComplianceReportQuery>,
+) -> SecurityResult<Json<Vec<ComplianceReport>>> {
+    Ok(Json(state.compliance_service.list_reports(None, query.report_type).await?))
+}
+
+async fn get_platform_compliance_report(
+    State(state): State<Arc<AppState>>,
+    Path(report_id): Path<Uuid>,
+) -> SecurityResult<Json<ComplianceReport>> {
+    Ok(Json(state.compliance_service.get_report(None, report_id).await?))
+}
+
+async fn download_platform_compliance_report(
+    State(state): State<Arc<AppState>>,
+    Path(report_id): Path<Uuid>,
+    Query(query): Query<ReportDownloadQuery>,
+) -> SecurityResult<Response> {
+    let format = query.format.unwrap_or_else(|| "json".to_string());
+    let content = state.compliance_service.export_report(None, report_id, &format).await?;
+    Ok(report_download(report_id, &format, content))
+}
+
+async fn list_key_rotation_schedules(
+    State(state): State<Arc<AppState>>,
+) -> SecurityResult<Json<Vec<KeyRotationSchedule>>> {
+    Ok(Json(state.key_service.list_schedules().await?))
+}
+
+async fn get_key_lifecycle(
+    State(state): State<Arc<AppState>>,
+    Path((purpose, owner_id)): Path<(KeyPurpose, String)>,
+) -> SecurityResult<Json<KeyLifecycle>> {
+    Ok(Json(state.key_service.lifecycle(purpose, &owner_id).await?))
+}
+
+/// Keys validators accept, with their secrets; for internal callers only
+async fn get_signing_key_set(
+    State(state): State<Arc<AppState>>,
+    Path((purpose, owner_id)): Path<(KeyPurpose, String)>,
+) -> SecurityResult<Json<SigningKeySet>> {
+    Ok(Json(state.key_service.key_set(purpose, &owner_id).await?))
+}
+
+/// Stage a new key; it becomes active once it has propagated
+async fn rotate_key(
+    State(state): State<Arc<AppState>>,
+    Path((purpose, owner_id)): Path<(KeyPurpose, String)>,
+    Json(request): Json<RotateKeyRequest>,
+) -> SecurityResult<(StatusCode, Json<ManagedKey>)> {
+    let key = state.key_service.rotate(purpose, &owner_id, &request.requested_by).await?;
+    Ok((StatusCode::ACCEPTED, Json(key)))
+}
+
+async fn set_key_rotation_schedule(
+    State(state): State<Arc<AppState>>,
+    Path((purpose, owner_id)): Path<(KeyPurpose, String)>,
+    Json(request): Json<SetKeyRotationScheduleRequest>,
+) -> SecurityResult<Json<KeyRotationSchedule>> {
+    Ok(Json(state.key_service.set_schedule(purpose, &owner_id, request).await?))
+}
+
+async fn get_dlp_policy(
+    State(state): State<Arc<AppState>>,
+    Path(tenant_id): Path<String>,
+    headers: HeaderMap,
+) -> SecurityResult<Json<DlpPolicy>> {
+    require_tenant(&headers, &tenant_id)?;
+    Ok(Json(state.dlp_service.get_policy(&tenant_id).await?))
+}
+
+async fn set_dlp_policy(
+    State(state): State<Arc<AppState>>,
+    Path(tenant_id): Path<String>,
+    headers: HeaderMap,
+    Json(request): Json<SetDlpPolicyRequest>,
+) -> SecurityResult<Json<DlpPolicy>> {
+    require_tenant(&headers, &tenant_id)?;
+    Ok(Json(state.dlp_service.set_policy(&tenant_id, request).await?))
+}
+
+/// Narrows the audit events a policy produced: DLP incidents, network denials
+#[derive(Debug, Deserialize)]
+struct PolicyEventQuery {
+    start_date: Option<DateTime<Utc>>,
+    end_date: Option<DateTime<Utc>>,
+    service: Option<String>,
+    outcome: Option<AuditOutcome>,
+    page: Option<i32>,
+    page_size: Option<i32>,
+}
+
+async fn list_dlp_incidents(
+    State(state): State<Arc<AppState>>,
+    Path(tenant_id): Path<String>,
+    headers: HeaderMap,
+    Query(query): Query<PolicyEventQuery>,
+) -> SecurityResult<Json<AuditLogResponse>> {
+    require_tenant(&headers, &tenant_id)?;
+
+    let filter = AuditLogFilter {
+        start_date: query.start_date,
+        end_date: query.end_date,
+        service: query.service,
+        outcome: query.outcome,
+        ..Default::default()
+    };
+    let page = query.page.unwrap_or(1).max(1);
+    let page_size = query.page_size.unwrap_or(50).clamp(1, 500);
+
+    Ok(Json(state.dlp_service.list_incidents(&tenant_id, filter, page, page_size).await?))
+}
+
+async fn get_network_policy(
+    State(state): State<Arc<AppState>>,
+    Path(tenant_id): Path<String>,
+    headers: HeaderMap,
+) -> SecurityResult<Json<NetworkPolicy>> {
+    require_tenant(&headers, &tenant_id)?;
+    Ok(Json(state.network_service.get_policy(&tenant_id).await?))
+}
+
+async fn set_network_policy(
+    State(state): State<Arc<AppState>>,
+    Path(tenant_id): Path<String>,
+    headers: HeaderMap,
+    Json(request): Json<SetNetworkPolicyRequest>,
+) -> SecurityResult<Json<NetworkPolicy>> {
+    require_tenant(&headers, &tenant_id)?;
+    Ok(Json(state.network_service.set_policy(&tenant_id, request).await?))
+}
+
+async fn list_network_denials(
+    State(state): State<Arc<AppState>>,
+    Path(tenant_id): Path<String>,
+    headers: HeaderMap,
+    Query(query): Query<PolicyEventQuery>,
+) -> SecurityResult<Json<AuditLogResponse>> {
+    require_tenant(&headers, &tenant_id)?;
+
+    let filter = AuditLogFilter {
+        start_date: query.start_date,
+        end_date: query.end_date,
+        service: query.service,
+        outcome: query.outcome,
+        ..Default::default()
+    };
+    let page = query.page.unwrap_or(1).max(1);
+    let page_size = query.page_size.unwrap_or(50).clamp(1, 500);
+
+    Ok(Json(state.network_service.list_denials(&tenant_id, filter, page, page_size).await?))
+}
+
+/// TOR and VPN ranges, for the gateway; platform-wide, not per tenant
+async fn list_anonymizer_ranges(
+    State(state): State<Arc<AppState>>,
+) -> SecurityResult<Json<Vec<AnonymizerRange>>> {
+    Ok(Json(state.network_service.anonymizer_ranges().await?))
+}
+
+async fn replace_anonymizer_ranges(
+    State(state): State<Arc<AppState>>,
+    Path(category): Path<AnonymizerCategory>,
+    Json(request): Json<ReplaceAnonymizerRangesRequest>,
+) -> SecurityResult<Json<AnonymizerRangeSummary>> {
+    Ok(Json(state.network_service.replace_anonymizer_ranges(category, request).await?))
+}
+
+async fn start_access_review(
+    State(state): State<Arc<AppState>>,
+    Path(tenant_id): Path<String>,
+    headers: HeaderMap,
+    Json(request): Json<StartAccessReviewRequest>,
+) -> SecurityResult<(StatusCode, Json<AccessReviewSummary>)> {
+    require_tenant(&headers, &tenant_id)?;
+    let summary = state.access_review_service.start_campaign(&tenant_id, request).await?;
+    Ok((StatusCode::CREATED, Json(summary)))
+}
+
+async fn list_access_reviews(
+    State(state): State<Arc<AppState>>,
+    Path(tenant_id): Path<String>,
+    headers: HeaderMap,
+) -> SecurityResult<Json<Vec<AccessReviewCampaign>>> {
+    require_tenant(&headers, &tenant_id)?;
+    Ok(Json(state.access_review_service.list_campaigns(&tenant_id).await?))
+}
+
+async fn get_access_review(
+    State(state): State<Arc<AppState>>,
+    Path((tenant_id, campaign_id)): Path<(String, Uuid)>,
+    headers: HeaderMap,
+) -> SecurityResult<Json<AccessReviewSummary>> {
+    require_tenant(&headers, &tenant_id)?;
+    Ok(Json(state.access_review_service.get_campaign(&tenant_id, campaign_id).await?))
+}
+
+#[derive(Debug, Deserialize)]
+struct AccessReviewItemQuery {
+    decision: Option<AccessReviewDecision>,
+}
+
+async fn list_access_review_items(
+    State(state): State<Arc<AppState>>,
+    Path((tenant_id, campaign_id)): Path<(String, Uuid)>,
+    headers: HeaderMap,
+    Query(query): Query<AccessReviewItemQuery>,
+) -> SecurityResult<Json<Vec<AccessReviewItem>>> {
+    require_tenant(&headers, &tenant_id)?;
+    Ok(Json(state.access_review_service.list_items(&tenant_id, campaign_id, query.decision).await?))
+}
+
+async fn attest_access_review(
+    State(state): State<Arc<AppState>>,
+    Path((tenant_id, campaign_id)): Path<(String, Uuid)>,
+    headers: HeaderMap,
+    Json(request): Json<AttestAccessRequest>,
+) -> SecurityResult<Json<Vec<AccessReviewItem>>> {
+    require_tenant(&headers, &tenant_id)?;
+    Ok(Json(state.access_review_service.attest(&tenant_id, campaign_id, request).await?))
+}
+
+/// Close a campaign before its deadline, revoking what nobody attested to
+async fn close_access_review(
+    State(state): State<Arc<AppState>>,
+    Path((tenant_id, campaign_id)): Path<(String, Uuid)>,
+    headers: HeaderMap,
+    Json(request): Json<CloseAccessReviewRequest>,
+) -> SecurityResult<Json<AccessReviewSummary>> {
+    require_tenant(&headers, &tenant_id)?;
+    Ok(Json(state.access_review_service.close_campaign(&tenant_id, campaign_id, &request.closed_by).await?))
+}
+
+async fn get_access_review_evidence(
+    State(state): State<Arc<AppState>>,
+    Path((tenant_id, campaign_id)): Path<(String, Uuid)>,
+    headers: HeaderMap,
+) -> SecurityResult<Json<serde_json::Value>> {
+    require_tenant(&headers, &tenant_id)?;
+    Ok(Json(state.access_review_service.evidence(&tenant_id, campaign_id).await?))
+}
+
+async fn get_access_review_schedule(
+    State(state): State<Arc<AppState>>,
+    Path(tenant_id): Path<String>,
+    headers: HeaderMap,
+) -> SecurityResult<Json<Option<AccessReviewSchedule>>> {
+    require_tenant(&headers, &tenant_id)?;
+    Ok(Json(state.access_review_service.get_schedule(&tenant_id).await?))
+}
+
+async fn set_access_review_schedule(
+    State(state): State<Arc<AppState>>,
+    Path(tenant_id): Path<String>,
+    headers: HeaderMap,
+    Json(request): Json<SetAccessReviewScheduleRequest>,
+) -> SecurityResult<Json<AccessReviewSchedule>> {
+    require_tenant(&headers, &tenant_id)?;
+    Ok(Json(state.access_review_service.set_schedule(&tenant_id, request).await?))
+}
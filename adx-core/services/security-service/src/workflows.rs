@@ -1,9 +1,12 @@
 use crate::{
     activities::SecurityActivities,
+    compliance::PLATFORM_SCOPE_ID,
     error::{SecurityError, SecurityResult},
     models::{
         GdprExportRequest, GdprDeletionRequest, SecurityScanRequest, ComplianceReportRequest,
-        DataRetentionPolicy, DeletionMethod, AuditOutcome
+        DataRetentionPolicy, DeletionMethod, AuditOutcome, ComplianceReportType, ComplianceStatus,
+        GenerateComplianceReportRequest, KeyPurpose, RiskLevel, StartAccessReviewRequest,
+        ContainmentActionStatus, ContainmentActionType, RequestContainmentRequest,
     },
 };
 use chrono::{DateTime, Utc, Duration};
@@ -102,6 +105,94 @@ pub struct ComplianceReportWorkflowResult {
     pub completed_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerateComplianceReportWorkflowRequest {
+    /// None reports on the whole platform
+    pub tenant_id: Option<String>,
+    pub report_type: ComplianceReportType,
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub requested_by: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GenerateComplianceReportWorkflowResult {
+    pub report_id: Uuid,
+    pub status: ComplianceStatus,
+    pub risk_level: RiskLevel,
+    pub compliance_score: f32,
+    pub failed_controls: i32,
+    /// Where auditors download the report
+    pub download_path: String,
+    pub completed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyRotationWorkflowRequest {
+    pub purpose: KeyPurpose,
+    /// 'platform' for JWT signing keys, otherwise the tenant or webhook
+    pub owner_id: String,
+    pub requested_by: String,
+    /// How long the staged key is published before it is used
+    pub propagation_minutes: i64,
+    /// Defaults to the owner's schedule, or the purpose's configured overlap
+    pub overlap_hours: Option<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct KeyRotationWorkflowResult {
+    pub kid: String,
+    pub activated_at: DateTime<Utc>,
+    /// When the replaced key stopped being accepted
+    pub previous_key_retired_at: DateTime<Utc>,
+    pub retired_keys: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessReviewWorkflowRequest {
+    pub tenant_id: String,
+    pub name: Option<String>,
+    /// How long reviewers have to attest
+    pub review_days: i32,
+    /// Revoke access nobody attested to when the review closes
+    pub auto_revoke: bool,
+    pub requested_by: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AccessReviewWorkflowResult {
+    pub campaign_id: Uuid,
+    pub total_items: i64,
+    pub approved: i64,
+    pub revoked: i64,
+    pub auto_revoked: i64,
+    pub revocation_failures: i64,
+    /// Hash of the archived evidence
+    pub evidence_hash: Option<String>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentContainmentWorkflowRequest {
+    pub tenant_id: String,
+    pub incident_id: Uuid,
+    pub action_type: ContainmentActionType,
+    pub target: String,
+    pub reason: String,
+    pub requested_by: String,
+    /// How long to wait for approval before the action expires
+    pub approval_timeout_minutes: i64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IncidentContainmentWorkflowResult {
+    pub action_id: Uuid,
+    pub status: ContainmentActionStatus,
+    pub approved_by: Option<String>,
+    pub executed_at: Option<DateTime<Utc>>,
+    pub error: Option<String>,
+}
+
 // GDPR Data Export Workflow
 #[workflow]
 pub async fn gdpr_data_export_workflow(
@@ -680,6 +771,101 @@ pub async fn compliance_report_workflow(
     })
 }
 
+// Template-based Compliance Report Workflow
+#[workflow]
+pub async fn generate_compliance_report_workflow(
+    request: GenerateComplianceReportWorkflowRequest,
+) -> WorkflowResult<GenerateComplianceReportWorkflowResult> {
+    let activity_options = ActivityOptions {
+        start_to_close_timeout: Some(Duration::minutes(30)),
+        retry_policy: Some(temporal_sdk::RetryPolicy {
+            maximum_attempts: Some(3),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    // Step 1: Validate report parameters
+    let validation_result = temporal_sdk::activity(activity_options.clone())
+        .call(
+            SecurityActivities::validate_compliance_report_request,
+            (
+                request.tenant_id.clone().unwrap_or_else(|| PLATFORM_SCOPE_ID.to_string()),
+                format!("{:?}", request.report_type),
+                request.period_start,
+                request.period_end,
+            ),
+        )
+        .await?;
+
+    if !validation_result {
+        return Err(temporal_sdk::WorkflowError::ApplicationError {
+            error_type: "ValidationError".to_string(),
+            message: "Invalid compliance report parameters".to_string(),
+            non_retryable: true,
+        });
+    }
+
+    // Step 2: Gather evidence; platform reports visit every tenant
+    let evidence = temporal_sdk::activity(
+        ActivityOptions {
+            start_to_close_timeout: Some(Duration::hours(2)),
+            ..activity_options.clone()
+        }
+    )
+    .call(
+        SecurityActivities::gather_compliance_evidence,
+        (
+            request.tenant_id.clone(),
+            request.report_type.clone(),
+            request.period_start,
+            request.period_end,
+        ),
+    )
+    .await?;
+
+    // Step 3: Assess the template's controls
+    let assessment = temporal_sdk::activity(activity_options.clone())
+        .call(
+            SecurityActivities::assess_compliance_evidence,
+            (request.report_type.clone(), evidence.clone()),
+        )
+        .await?;
+
+    // Step 4: Store the report with its evidence
+    let report = temporal_sdk::activity(activity_options.clone())
+        .call(
+            SecurityActivities::store_compliance_report,
+            (
+                request.tenant_id.clone(),
+                GenerateComplianceReportRequest {
+                    report_type: request.report_type.clone(),
+                    period_start: request.period_start,
+                    period_end: request.period_end,
+                    requested_by: request.requested_by.clone(),
+                },
+                assessment.clone(),
+                evidence,
+            ),
+        )
+        .await?;
+
+    let download_path = match request.tenant_id {
+        Some(_) => format!("/api/v1/compliance/reports/{}/download", report.id),
+        None => format!("/api/v1/compliance/platform/reports/{}/download", report.id),
+    };
+
+    Ok(GenerateComplianceReportWorkflowResult {
+        report_id: report.id,
+        status: assessment.status,
+        risk_level: assessment.risk_level,
+        compliance_score: assessment.compliance_score,
+        failed_controls: assessment.controls.iter().filter(|control| !control.passed).count() as i32,
+        download_path,
+        completed_at: Utc::now(),
+    })
+}
+
 // Automated Security Response Workflow
 #[workflow]
 pub async fn automated_security_response_workflow(
@@ -781,4 +967,194 @@ pub struct SecurityResponseAction {
     pub action_type: String,
     pub target: String,
     pub parameters: HashMap<String, serde_json::Value>,
-}
\ No newline at end of file
+}
+// Key Rotation Workflow
+// Stages a key, waits for validators to pick it up, activates it, then waits
+// out the overlap window before retiring the key it replaced.
+#[workflow]
+pub async fn key_rotation_workflow(
+    request: KeyRotationWorkflowRequest,
+) -> WorkflowResult<KeyRotationWorkflowResult> {
+    let activity_options = ActivityOptions {
+        start_to_close_timeout: Some(Duration::minutes(5)),
+        retry_policy: Some(temporal_sdk::RetryPolicy {
+            maximum_attempts: Some(5),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    // Step 1: Stage the new key so validators start accepting it
+    let kid = temporal_sdk::activity(activity_options.clone())
+        .call(
+            SecurityActivities::stage_rotation_key,
+            (request.purpose, request.owner_id.clone(), request.requested_by.clone()),
+        )
+        .await?;
+
+    // Step 2: Give validators time to refresh their key sets
+    temporal_sdk::workflow::sleep(std::time::Duration::from_secs(
+        request.propagation_minutes.max(0) as u64 * 60,
+    ))
+    .await;
+
+    // Step 3: Sign and encrypt with the new key; the old one starts retiring
+    let activated_at = Utc::now();
+    let previous_key_retired_at = temporal_sdk::activity(activity_options.clone())
+        .call(
+            SecurityActivities::activate_rotation_key,
+            (kid.clone(), request.overlap_hours),
+        )
+        .await?;
+
+    // Step 4: Wait out the overlap window, then stop accepting the old key
+    let overlap = (previous_key_retired_at - Utc::now()).to_std().unwrap_or_default();
+    temporal_sdk::workflow::sleep(overlap).await;
+
+    let retired_keys = temporal_sdk::activity(activity_options)
+        .call(
+            SecurityActivities::retire_expired_keys,
+            (request.purpose, request.owner_id.clone()),
+        )
+        .await?;
+
+    Ok(KeyRotationWorkflowResult {
+        kid,
+        activated_at,
+        previous_key_retired_at,
+        retired_keys,
+    })
+}
+
+// Access Review Workflow
+// Snapshots the tenant's access for review, waits out the review period,
+// then closes the campaign: unattested access is revoked and the outcome
+// archived as compliance evidence.
+#[workflow]
+pub async fn access_review_workflow(
+    request: AccessReviewWorkflowRequest,
+) -> WorkflowResult<AccessReviewWorkflowResult> {
+    let activity_options = ActivityOptions {
+        start_to_close_timeout: Some(Duration::minutes(15)),
+        retry_policy: Some(temporal_sdk::RetryPolicy {
+            maximum_attempts: Some(3),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    // Step 1: Snapshot user roles and module grants as review items
+    let started = temporal_sdk::activity(activity_options.clone())
+        .call(
+            SecurityActivities::start_access_review,
+            (
+                request.tenant_id.clone(),
+                StartAccessReviewRequest {
+                    name: request.name.clone(),
+                    review_days: Some(request.review_days),
+                    auto_revoke: Some(request.auto_revoke),
+                    created_by: request.requested_by.clone(),
+                },
+            ),
+        )
+        .await?;
+    let campaign_id = started.campaign.id;
+
+    // Step 2: Give reviewers until the deadline to attest
+    let review_period = (started.campaign.deadline - Utc::now()).to_std().unwrap_or_default();
+    temporal_sdk::workflow::sleep(review_period).await;
+
+    // Step 3: Revoke what nobody attested to and archive the evidence
+    let closed = temporal_sdk::activity(activity_options)
+        .call(
+            SecurityActivities::close_access_review,
+            (request.tenant_id.clone(), campaign_id, request.requested_by.clone()),
+        )
+        .await?;
+
+    Ok(AccessReviewWorkflowResult {
+        campaign_id,
+        total_items: closed.total_items,
+        approved: closed.approved,
+        revoked: closed.revoked,
+        auto_revoked: closed.auto_revoked,
+        revocation_failures: closed.revocation_failures,
+        evidence_hash: closed.campaign.evidence_hash,
+        completed_at: closed.campaign.completed_at,
+    })
+}
+
+// Incident Containment Workflow
+// Requests a containment action for an incident. Actions that need approval
+// are polled until someone other than the requester decides on them, and
+// expire when nobody does before the timeout; approved actions are then run.
+#[workflow]
+pub async fn incident_containment_workflow(
+    request: IncidentContainmentWorkflowRequest,
+) -> WorkflowResult<IncidentContainmentWorkflowResult> {
+    let activity_options = ActivityOptions {
+        start_to_close_timeout: Some(Duration::minutes(5)),
+        retry_policy: Some(temporal_sdk::RetryPolicy {
+            maximum_attempts: Some(3),
+            ..Default::default()
+        }),
+        ..Default::default()
+    };
+
+    // Step 1: Record the request; actions without approval run straight away
+    let mut action = temporal_sdk::activity(activity_options.clone())
+        .call(
+            SecurityActivities::request_containment_action,
+            (
+                request.tenant_id.clone(),
+                request.incident_id,
+                RequestContainmentRequest {
+                    action_type: request.action_type,
+                    target: request.target.clone(),
+                    reason: request.reason.clone(),
+                    requested_by: request.requested_by.clone(),
+                },
+            ),
+        )
+        .await?;
+
+    // Step 2: Wait for an approver, checking once a minute
+    let expires_at = action.requested_at + Duration::minutes(request.approval_timeout_minutes.max(1));
+    while action.status == ContainmentActionStatus::PendingApproval {
+        if Utc::now() >= expires_at {
+            action = temporal_sdk::activity(activity_options.clone())
+                .call(
+                    SecurityActivities::expire_containment_action,
+                    (request.tenant_id.clone(), request.incident_id, action.id),
+                )
+                .await?;
+            break;
+        }
+
+        temporal_sdk::workflow::sleep(std::time::Duration::from_secs(60)).await;
+        action = temporal_sdk::activity(activity_options.clone())
+            .call(
+                SecurityActivities::get_containment_action,
+                (request.tenant_id.clone(), request.incident_id, action.id),
+            )
+            .await?;
+    }
+
+    // Step 3: Run it once approved; approving through the API may already have
+    if action.status == ContainmentActionStatus::Approved {
+        action = temporal_sdk::activity(activity_options)
+            .call(
+                SecurityActivities::execute_containment_action,
+                (request.tenant_id.clone(), request.incident_id, action.id),
+            )
+            .await?;
+    }
+
+    Ok(IncidentContainmentWorkflowResult {
+        action_id: action.id,
+        status: action.status,
+        approved_by: action.decided_by,
+        executed_at: action.executed_at,
+        error: action.error,
+    })
+}
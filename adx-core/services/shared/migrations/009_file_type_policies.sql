@@ -0,0 +1,21 @@
+-- File type policies
+-- Per-tenant allow/block lists enforced against sniffed upload content
+
+CREATE TABLE IF NOT EXISTS file_type_policies (
+    tenant_id UUID PRIMARY KEY REFERENCES tenants(id) ON DELETE CASCADE,
+    allowed_mime_types TEXT[] NOT NULL DEFAULT '{}', -- Empty means every non-blocked type
+    blocked_mime_types TEXT[] NOT NULL DEFAULT '{}', -- Supports 'type/*' wildcards
+    blocked_extensions TEXT[] NOT NULL DEFAULT '{}',
+    enforce_content_match BOOLEAN NOT NULL DEFAULT true,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE file_type_policies ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY tenant_isolation_file_type_policies ON file_type_policies
+    FOR ALL
+    TO PUBLIC
+    USING (tenant_id = current_setting('app.current_tenant_id', true)::UUID);
+
+CREATE TRIGGER update_file_type_policies_updated_at BEFORE UPDATE ON file_type_policies FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
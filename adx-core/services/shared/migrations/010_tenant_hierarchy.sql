@@ -0,0 +1,10 @@
+-- Tenant hierarchy
+-- Parent/child links for reseller and department sub-tenants
+
+ALTER TABLE tenants ADD COLUMN IF NOT EXISTS parent_tenant_id UUID REFERENCES tenants(id) ON DELETE RESTRICT;
+ALTER TABLE tenants ADD COLUMN IF NOT EXISTS inherit_settings BOOLEAN NOT NULL DEFAULT false;
+
+ALTER TABLE tenants DROP CONSTRAINT IF EXISTS tenants_parent_not_self;
+ALTER TABLE tenants ADD CONSTRAINT tenants_parent_not_self CHECK (parent_tenant_id IS NULL OR parent_tenant_id <> id);
+
+CREATE INDEX IF NOT EXISTS idx_tenants_parent_tenant_id ON tenants(parent_tenant_id) WHERE parent_tenant_id IS NOT NULL;
@@ -0,0 +1,33 @@
+-- Feature flags
+-- Flag definitions with tier and percentage rollout rules, plus per-tenant overrides
+
+CREATE TABLE IF NOT EXISTS feature_flags (
+    key VARCHAR(64) PRIMARY KEY,
+    description TEXT,
+    default_enabled BOOLEAN NOT NULL DEFAULT false,
+    enabled_tiers TEXT[] NOT NULL DEFAULT '{}',
+    rollout_percentage SMALLINT NOT NULL DEFAULT 0 CHECK (rollout_percentage BETWEEN 0 AND 100),
+    is_active BOOLEAN NOT NULL DEFAULT true, -- Inactive flags are off for every tenant
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS tenant_feature_flag_overrides (
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    flag_key VARCHAR(64) NOT NULL REFERENCES feature_flags(key) ON DELETE CASCADE,
+    enabled BOOLEAN NOT NULL,
+    reason TEXT,
+    updated_by UUID,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tenant_id, flag_key)
+);
+
+ALTER TABLE tenant_feature_flag_overrides ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY tenant_isolation_feature_flag_overrides ON tenant_feature_flag_overrides
+    FOR ALL
+    TO PUBLIC
+    USING (tenant_id = current_setting('app.current_tenant_id', true)::UUID);
+
+CREATE TRIGGER update_feature_flags_updated_at BEFORE UPDATE ON feature_flags FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+CREATE TRIGGER update_tenant_feature_flag_overrides_updated_at BEFORE UPDATE ON tenant_feature_flag_overrides FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
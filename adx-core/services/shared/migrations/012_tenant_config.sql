@@ -0,0 +1,36 @@
+-- Tenant configuration
+-- Service-owned settings namespaces with JSON schemas, plus sparse per-tenant values
+
+CREATE TABLE IF NOT EXISTS config_namespaces (
+    name VARCHAR(64) PRIMARY KEY,
+    description TEXT,
+    owner_service VARCHAR(100) NOT NULL,
+    schema JSONB NOT NULL,
+    default_value JSONB NOT NULL DEFAULT '{}',
+    version INTEGER NOT NULL DEFAULT 1, -- Bumped when the schema or defaults change
+    is_builtin BOOLEAN NOT NULL DEFAULT false,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS tenant_config_values (
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    namespace VARCHAR(64) NOT NULL REFERENCES config_namespaces(name) ON DELETE CASCADE,
+    value JSONB NOT NULL, -- Only the keys the tenant has set; merged over default_value on read
+    version BIGINT NOT NULL DEFAULT 1,
+    updated_by UUID,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tenant_id, namespace)
+);
+
+CREATE INDEX IF NOT EXISTS idx_tenant_config_values_namespace ON tenant_config_values(namespace);
+
+ALTER TABLE tenant_config_values ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY tenant_isolation_tenant_config_values ON tenant_config_values
+    FOR ALL
+    TO PUBLIC
+    USING (tenant_id = current_setting('app.current_tenant_id', true)::UUID);
+
+CREATE TRIGGER update_config_namespaces_updated_at BEFORE UPDATE ON config_namespaces FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+CREATE TRIGGER update_tenant_config_values_updated_at BEFORE UPDATE ON tenant_config_values FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
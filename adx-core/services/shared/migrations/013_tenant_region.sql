@@ -0,0 +1,6 @@
+-- Tenant region
+-- Home region for each tenant's data; NULL means the deployment's default region
+
+ALTER TABLE tenants ADD COLUMN IF NOT EXISTS region VARCHAR(32);
+
+CREATE INDEX IF NOT EXISTS idx_tenants_region ON tenants(region) WHERE region IS NOT NULL;
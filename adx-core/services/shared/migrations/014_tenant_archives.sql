@@ -0,0 +1,31 @@
+-- Tenant archives
+-- Cold storage location and restore SLA tracking for archived tenants
+
+CREATE TABLE IF NOT EXISTS tenant_archives (
+    tenant_id UUID PRIMARY KEY REFERENCES tenants(id) ON DELETE CASCADE,
+    archive_id UUID NOT NULL,
+    location TEXT NOT NULL,
+    storage_class VARCHAR(32) NOT NULL DEFAULT 'cold',
+    compressed_size_bytes BIGINT NOT NULL,
+    uncompressed_size_bytes BIGINT NOT NULL,
+    checksum VARCHAR(64) NOT NULL,
+    previous_status VARCHAR(20) NOT NULL, -- Status the tenant returns to on restore
+    reason TEXT,
+    archived_by UUID,
+    archived_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    restore_requested_at TIMESTAMPTZ,
+    restore_requested_by UUID,
+    restore_sla_deadline TIMESTAMPTZ,
+    restore_completed_at TIMESTAMPTZ,
+    restore_sla_breached BOOLEAN NOT NULL DEFAULT false
+);
+
+CREATE INDEX IF NOT EXISTS idx_tenant_archives_pending_restores ON tenant_archives(restore_sla_deadline)
+    WHERE restore_requested_at IS NOT NULL AND restore_completed_at IS NULL;
+
+ALTER TABLE tenant_archives ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY tenant_isolation_tenant_archives ON tenant_archives
+    FOR ALL
+    TO PUBLIC
+    USING (tenant_id = current_setting('app.current_tenant_id', true)::UUID);
@@ -0,0 +1,45 @@
+-- Tenant lifecycle events
+-- Platform-wide event log and webhook subscriptions for tenant lifecycle changes.
+-- These tables belong to platform admins, so they carry no tenant RLS policy.
+
+CREATE TABLE IF NOT EXISTS tenant_lifecycle_events (
+    sequence BIGSERIAL PRIMARY KEY,
+    event_id UUID NOT NULL UNIQUE,
+    event_type VARCHAR(50) NOT NULL, -- tenant.created, tenant.suspended, ...
+    tenant_id UUID NOT NULL, -- No foreign key: terminated tenants keep their history
+    tenant_name VARCHAR(255) NOT NULL,
+    subscription_tier VARCHAR(50) NOT NULL,
+    status VARCHAR(20) NOT NULL,
+    data JSONB NOT NULL DEFAULT '{}',
+    occurred_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_tenant_lifecycle_events_tenant ON tenant_lifecycle_events(tenant_id, sequence);
+
+-- Distinct from the per-tenant tenant_webhooks integrations table in 006
+CREATE TABLE IF NOT EXISTS tenant_lifecycle_webhooks (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    url TEXT NOT NULL,
+    secret TEXT NOT NULL,
+    description TEXT,
+    event_types TEXT[] NOT NULL DEFAULT '{}', -- Empty means every event type
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS tenant_lifecycle_webhook_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    webhook_id UUID NOT NULL REFERENCES tenant_lifecycle_webhooks(id) ON DELETE CASCADE,
+    event_id UUID NOT NULL REFERENCES tenant_lifecycle_events(event_id) ON DELETE CASCADE,
+    event_type VARCHAR(50) NOT NULL,
+    attempt INTEGER NOT NULL,
+    status_code INTEGER,
+    success BOOLEAN NOT NULL,
+    error TEXT,
+    attempted_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_tenant_lifecycle_webhook_deliveries_webhook ON tenant_lifecycle_webhook_deliveries(webhook_id, attempted_at DESC);
+
+CREATE TRIGGER update_tenant_lifecycle_webhooks_updated_at BEFORE UPDATE ON tenant_lifecycle_webhooks FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,23 @@
+-- Tenant deletions
+-- Staged deletion records: soft delete, retention period, per-service purge
+-- and the destruction certificate issued by security-service. Records outlive
+-- the tenant as compliance evidence, so they have no foreign key and no tenant
+-- RLS policy; only platform admins read them.
+
+CREATE TABLE IF NOT EXISTS tenant_deletions (
+    tenant_id UUID PRIMARY KEY, -- No foreign key: the tenant row is removed by the purge
+    tenant_name VARCHAR(255) NOT NULL,
+    stage VARCHAR(20) NOT NULL DEFAULT 'soft_deleted', -- soft_deleted, purging, purged, cancelled
+    previous_status VARCHAR(20) NOT NULL, -- Status the tenant returns to if cancelled
+    requested_by UUID NOT NULL,
+    reason TEXT,
+    soft_deleted_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    purge_after TIMESTAMPTZ NOT NULL,
+    purged_services JSONB NOT NULL DEFAULT '[]', -- [{service, records_deleted, purged_at}]
+    purged_at TIMESTAMPTZ,
+    certificate_id UUID, -- data_destruction_certificates.id in security-service
+    cancelled_at TIMESTAMPTZ
+);
+
+CREATE INDEX IF NOT EXISTS idx_tenant_deletions_purge_due ON tenant_deletions(purge_after)
+    WHERE stage = 'soft_deleted';
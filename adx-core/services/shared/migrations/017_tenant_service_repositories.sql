@@ -0,0 +1,66 @@
+-- Tenant service repositories
+-- Columns and tables the tenant-service Postgres repositories need, plus
+-- platform access policies for admin reads that span tenants
+
+-- The custom tier is only sold through sales; existing rows are unaffected
+ALTER TYPE subscription_tier ADD VALUE IF NOT EXISTS 'custom';
+
+-- Full lifecycle status; is_active is kept in sync for services that only read the flag
+ALTER TABLE tenants ADD COLUMN IF NOT EXISTS status VARCHAR(20) NOT NULL DEFAULT 'active'; -- active, suspended, pending, cancelled, read_only, archived, pending_deletion
+UPDATE tenants SET status = 'suspended' WHERE is_active = false AND status = 'active';
+
+CREATE INDEX IF NOT EXISTS idx_tenants_status ON tenants(status);
+
+-- Single role and invitation tracking per membership; roles and is_active are kept in sync
+ALTER TABLE tenant_memberships ADD COLUMN IF NOT EXISTS role VARCHAR(20) NOT NULL DEFAULT 'member'; -- owner, admin, member, guest
+ALTER TABLE tenant_memberships ADD COLUMN IF NOT EXISTS status VARCHAR(20) NOT NULL DEFAULT 'active'; -- active, invited, suspended, removed
+ALTER TABLE tenant_memberships ADD COLUMN IF NOT EXISTS invited_by UUID;
+ALTER TABLE tenant_memberships ADD COLUMN IF NOT EXISTS invited_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+ALTER TABLE tenant_memberships ADD COLUMN IF NOT EXISTS created_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+ALTER TABLE tenant_memberships ADD COLUMN IF NOT EXISTS updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW();
+ALTER TABLE tenant_memberships ALTER COLUMN joined_at DROP NOT NULL; -- NULL until an invited user accepts
+
+CREATE TRIGGER update_tenant_memberships_updated_at BEFORE UPDATE ON tenant_memberships FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Provisioning templates; built-in templates are seeded by tenant-service on startup
+CREATE TABLE IF NOT EXISTS tenant_templates (
+    name VARCHAR(64) PRIMARY KEY,
+    display_name VARCHAR(255) NOT NULL,
+    description TEXT,
+    subscription_tier VARCHAR(20) NOT NULL,
+    quotas JSONB NOT NULL DEFAULT '{}',
+    features TEXT[] NOT NULL DEFAULT '{}',
+    default_roles JSONB NOT NULL DEFAULT '[]',
+    enabled_modules TEXT[] NOT NULL DEFAULT '{}',
+    sample_data TEXT[] NOT NULL DEFAULT '{}',
+    settings JSONB, -- NULL means the platform default settings
+    is_builtin BOOLEAN NOT NULL DEFAULT false,
+    is_active BOOLEAN NOT NULL DEFAULT true,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER update_tenant_templates_updated_at BEFORE UPDATE ON tenant_templates FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Platform admin reads across tenants (a user's memberships, every archive,
+-- every tenant's value in a namespace) set app.platform_access for the
+-- length of one transaction instead of a tenant id
+CREATE POLICY platform_access_tenant_memberships ON tenant_memberships
+    FOR ALL
+    TO PUBLIC
+    USING (current_setting('app.platform_access', true) = 'on');
+
+CREATE POLICY platform_access_feature_flag_overrides ON tenant_feature_flag_overrides
+    FOR ALL
+    TO PUBLIC
+    USING (current_setting('app.platform_access', true) = 'on');
+
+CREATE POLICY platform_access_tenant_config_values ON tenant_config_values
+    FOR ALL
+    TO PUBLIC
+    USING (current_setting('app.platform_access', true) = 'on');
+
+CREATE POLICY platform_access_tenant_archives ON tenant_archives
+    FOR ALL
+    TO PUBLIC
+    USING (current_setting('app.platform_access', true) = 'on');
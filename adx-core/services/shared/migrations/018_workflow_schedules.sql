@@ -0,0 +1,53 @@
+-- Workflow schedules
+-- Recurring workflow schedules run through Temporal schedules, per-tenant
+-- calendars of excluded dates and per-tenant schedule limits
+
+-- Named per-tenant calendars; schedules skip the excluded local dates
+CREATE TABLE IF NOT EXISTS workflow_calendars (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    name VARCHAR(100) NOT NULL,
+    description TEXT,
+    timezone VARCHAR(64) NOT NULL DEFAULT 'UTC', -- default for schedules created on this calendar
+    excluded_dates DATE[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(tenant_id, name)
+);
+
+-- schedule_id doubles as the Temporal schedule id; is_enabled is false while paused
+ALTER TABLE workflow_schedules ADD COLUMN IF NOT EXISTS name VARCHAR(255);
+ALTER TABLE workflow_schedules ADD COLUMN IF NOT EXISTS description TEXT;
+ALTER TABLE workflow_schedules ADD COLUMN IF NOT EXISTS timezone VARCHAR(64) NOT NULL DEFAULT 'UTC';
+ALTER TABLE workflow_schedules ADD COLUMN IF NOT EXISTS jitter_seconds INTEGER NOT NULL DEFAULT 0 CHECK (jitter_seconds >= 0);
+ALTER TABLE workflow_schedules ADD COLUMN IF NOT EXISTS calendar_id UUID REFERENCES workflow_calendars(id) ON DELETE RESTRICT;
+ALTER TABLE workflow_schedules ADD COLUMN IF NOT EXISTS paused_reason TEXT;
+ALTER TABLE workflow_schedules ADD COLUMN IF NOT EXISTS paused_at TIMESTAMPTZ;
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_workflow_schedules_tenant_name ON workflow_schedules(tenant_id, name) WHERE name IS NOT NULL;
+CREATE INDEX IF NOT EXISTS idx_workflow_schedules_calendar_id ON workflow_schedules(calendar_id);
+
+-- Overrides of the service-wide schedule limits; tenants without a row get the defaults
+CREATE TABLE IF NOT EXISTS workflow_schedule_limits (
+    tenant_id UUID PRIMARY KEY REFERENCES tenants(id) ON DELETE CASCADE,
+    max_schedules INTEGER NOT NULL CHECK (max_schedules >= 0),
+    min_interval_seconds INTEGER NOT NULL CHECK (min_interval_seconds >= 60),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE workflow_calendars ENABLE ROW LEVEL SECURITY;
+ALTER TABLE workflow_schedule_limits ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY tenant_isolation_workflow_calendars ON workflow_calendars
+    FOR ALL TO PUBLIC
+    USING (tenant_id = current_setting('app.current_tenant_id', true)::UUID);
+
+CREATE POLICY tenant_isolation_workflow_schedule_limits ON workflow_schedule_limits
+    FOR ALL TO PUBLIC
+    USING (tenant_id = current_setting('app.current_tenant_id', true)::UUID);
+
+CREATE INDEX IF NOT EXISTS idx_workflow_calendars_tenant_id ON workflow_calendars(tenant_id);
+
+CREATE TRIGGER update_workflow_calendars_updated_at BEFORE UPDATE ON workflow_calendars FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+CREATE TRIGGER update_workflow_schedule_limits_updated_at BEFORE UPDATE ON workflow_schedule_limits FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,18 @@
+-- Workflow templates
+-- Templates written in the workflow template DSL and the link from
+-- workflow executions back to the template they were started from
+
+-- definition holds the template document's parameters, steps, outputs and
+-- error handling; input_schema is the JSON schema derived from its parameters
+ALTER TABLE workflow_templates ADD COLUMN IF NOT EXISTS definition JSONB NOT NULL DEFAULT '{}';
+ALTER TABLE workflow_templates ADD COLUMN IF NOT EXISTS category VARCHAR(100) NOT NULL DEFAULT '';
+ALTER TABLE workflow_templates ADD COLUMN IF NOT EXISTS tags TEXT[] NOT NULL DEFAULT '{}';
+ALTER TABLE workflow_templates ADD COLUMN IF NOT EXISTS version INTEGER NOT NULL DEFAULT 1 CHECK (version >= 1);
+
+-- Deleting a template keeps the history of runs started from it
+ALTER TABLE workflow_executions ADD COLUMN IF NOT EXISTS template_id UUID REFERENCES workflow_templates(id) ON DELETE SET NULL;
+ALTER TABLE workflow_executions ADD COLUMN IF NOT EXISTS template_version INTEGER;
+
+CREATE INDEX IF NOT EXISTS idx_workflow_templates_category ON workflow_templates(category);
+CREATE INDEX IF NOT EXISTS idx_workflow_templates_tags ON workflow_templates USING GIN(tags);
+CREATE INDEX IF NOT EXISTS idx_workflow_executions_template_id ON workflow_executions(template_id) WHERE template_id IS NOT NULL;
@@ -0,0 +1,42 @@
+-- Workflow approvals
+-- Approval tasks that pause template workflows until an assignee approves
+-- or rejects them
+
+-- One task per approval step of a workflow run; assignees grow as the
+-- task escalates
+CREATE TABLE IF NOT EXISTS workflow_approvals (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    workflow_id VARCHAR(255) NOT NULL,
+    step_id VARCHAR(100) NOT NULL,
+    title VARCHAR(255) NOT NULL,
+    description TEXT NOT NULL DEFAULT '',
+    assignee_users UUID[] NOT NULL DEFAULT '{}',
+    assignee_roles TEXT[] NOT NULL DEFAULT '{}',
+    requested_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    allow_self_approval BOOLEAN NOT NULL DEFAULT false,
+    context JSONB NOT NULL DEFAULT '{}', -- the step's resolved parameters
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'approved', 'rejected', 'expired')),
+    escalation_level INTEGER NOT NULL DEFAULT 0 CHECK (escalation_level >= 0),
+    escalated_at TIMESTAMPTZ,
+    due_at TIMESTAMPTZ NOT NULL,
+    decided_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    decision_comment TEXT,
+    decided_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(workflow_id, step_id)
+);
+
+ALTER TABLE workflow_approvals ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY tenant_isolation_workflow_approvals ON workflow_approvals
+    FOR ALL TO PUBLIC
+    USING (tenant_id = current_setting('app.current_tenant_id', true)::UUID);
+
+CREATE INDEX IF NOT EXISTS idx_workflow_approvals_tenant_status ON workflow_approvals(tenant_id, status);
+CREATE INDEX IF NOT EXISTS idx_workflow_approvals_assignee_users ON workflow_approvals USING GIN(assignee_users);
+CREATE INDEX IF NOT EXISTS idx_workflow_approvals_assignee_roles ON workflow_approvals USING GIN(assignee_roles);
+CREATE INDEX IF NOT EXISTS idx_workflow_approvals_requested_by ON workflow_approvals(requested_by);
+
+CREATE TRIGGER update_workflow_approvals_updated_at BEFORE UPDATE ON workflow_approvals FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,40 @@
+-- Workflow dead letters
+-- Runs that failed for good, kept with their input so operators can fix and
+-- resubmit them
+
+CREATE TABLE IF NOT EXISTS workflow_dead_letters (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    workflow_id VARCHAR(255) NOT NULL,
+    run_id VARCHAR(255),
+    workflow_type VARCHAR(100) NOT NULL,
+    task_queue VARCHAR(255) NOT NULL,
+    input_data JSONB NOT NULL DEFAULT '{}',
+    original_input_data JSONB NOT NULL DEFAULT '{}', -- input as the failed run received it
+    failure_message TEXT NOT NULL,
+    failure_details JSONB NOT NULL DEFAULT '{}',
+    started_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'resubmitted', 'discarded')),
+    input_edited_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    input_edited_at TIMESTAMPTZ,
+    resubmit_count INTEGER NOT NULL DEFAULT 0 CHECK (resubmit_count >= 0),
+    resubmitted_workflow_id VARCHAR(255),
+    resolved_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    resolution_note TEXT,
+    resolved_at TIMESTAMPTZ,
+    failed_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(workflow_id)
+);
+
+ALTER TABLE workflow_dead_letters ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY tenant_isolation_workflow_dead_letters ON workflow_dead_letters
+    FOR ALL TO PUBLIC
+    USING (tenant_id = current_setting('app.current_tenant_id', true)::UUID);
+
+CREATE INDEX IF NOT EXISTS idx_workflow_dead_letters_tenant_status ON workflow_dead_letters(tenant_id, status, failed_at DESC);
+CREATE INDEX IF NOT EXISTS idx_workflow_dead_letters_workflow_type ON workflow_dead_letters(tenant_id, workflow_type);
+
+CREATE TRIGGER update_workflow_dead_letters_updated_at BEFORE UPDATE ON workflow_dead_letters FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,24 @@
+-- Workflow task queue assignments
+-- Tenants moved off the default task queues, onto their tier's queues or
+-- queues of their own; tenants without a row use the default queues. A
+-- dedicated queue belongs to one tenant only.
+
+CREATE TABLE IF NOT EXISTS workflow_task_queue_assignments (
+    tenant_id UUID PRIMARY KEY REFERENCES tenants(id) ON DELETE CASCADE,
+    tier VARCHAR(50),
+    dedicated_queue VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    CHECK ((tier IS NULL) <> (dedicated_queue IS NULL))
+);
+
+ALTER TABLE workflow_task_queue_assignments ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY tenant_isolation_workflow_task_queue_assignments ON workflow_task_queue_assignments
+    FOR ALL TO PUBLIC
+    USING (tenant_id = current_setting('app.current_tenant_id', true)::UUID);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_workflow_task_queue_assignments_dedicated_queue
+    ON workflow_task_queue_assignments(dedicated_queue) WHERE dedicated_queue IS NOT NULL;
+
+CREATE TRIGGER update_workflow_task_queue_assignments_updated_at BEFORE UPDATE ON workflow_task_queue_assignments FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,44 @@
+-- Workflow costs and budgets
+-- One row per metered workflow run with its activity time, retries and
+-- downstream service calls priced at the service's rates, plus an optional
+-- monthly budget per tenant. Tenants without a budget row are unlimited.
+
+CREATE TABLE IF NOT EXISTS workflow_run_costs (
+    id UUID PRIMARY KEY DEFAULT uuid_generate_v4(),
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    workflow_id VARCHAR(255) NOT NULL,
+    workflow_type VARCHAR(100) NOT NULL,
+    activity_count INTEGER NOT NULL DEFAULT 0,
+    activity_time_ms BIGINT NOT NULL DEFAULT 0,
+    retry_count INTEGER NOT NULL DEFAULT 0,
+    failed_calls INTEGER NOT NULL DEFAULT 0,
+    service_usage JSONB NOT NULL DEFAULT '{}',
+    compute_cost DOUBLE PRECISION NOT NULL DEFAULT 0,
+    api_cost DOUBLE PRECISION NOT NULL DEFAULT 0,
+    total_cost DOUBLE PRECISION NOT NULL DEFAULT 0,
+    recorded_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE (tenant_id, workflow_id)
+);
+
+CREATE TABLE IF NOT EXISTS workflow_budgets (
+    tenant_id UUID PRIMARY KEY REFERENCES tenants(id) ON DELETE CASCADE,
+    monthly_limit DOUBLE PRECISION CHECK (monthly_limit >= 0),
+    warn_at_percent INTEGER NOT NULL DEFAULT 80 CHECK (warn_at_percent BETWEEN 1 AND 100),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE workflow_run_costs ENABLE ROW LEVEL SECURITY;
+ALTER TABLE workflow_budgets ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY tenant_isolation_workflow_run_costs ON workflow_run_costs
+    FOR ALL TO PUBLIC
+    USING (tenant_id = current_setting('app.current_tenant_id', true)::UUID);
+
+CREATE POLICY tenant_isolation_workflow_budgets ON workflow_budgets
+    FOR ALL TO PUBLIC
+    USING (tenant_id = current_setting('app.current_tenant_id', true)::UUID);
+
+CREATE INDEX IF NOT EXISTS idx_workflow_run_costs_tenant_recorded ON workflow_run_costs(tenant_id, recorded_at);
+
+CREATE TRIGGER update_workflow_budgets_updated_at BEFORE UPDATE ON workflow_budgets FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
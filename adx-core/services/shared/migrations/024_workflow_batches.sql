@@ -0,0 +1,49 @@
+-- Workflow batches
+-- A batch runs one workflow type once per input, a bounded number of
+-- children at a time. Each child keeps its input, result and status so the
+-- batch can report progress and return results in input order.
+
+CREATE TABLE IF NOT EXISTS workflow_batches (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    workflow_type VARCHAR(100) NOT NULL,
+    parallelism INTEGER NOT NULL CHECK (parallelism > 0),
+    fail_fast BOOLEAN NOT NULL DEFAULT false,
+    total_children INTEGER NOT NULL CHECK (total_children > 0),
+    status VARCHAR(20) NOT NULL DEFAULT 'running' CHECK (status IN ('running', 'completed', 'failed', 'cancelled')),
+    cancel_requested BOOLEAN NOT NULL DEFAULT false,
+    created_by UUID NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ
+);
+
+CREATE TABLE IF NOT EXISTS workflow_batch_children (
+    batch_id UUID NOT NULL REFERENCES workflow_batches(id) ON DELETE CASCADE,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    child_index INTEGER NOT NULL CHECK (child_index >= 0),
+    workflow_id VARCHAR(255) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'running', 'completed', 'failed', 'skipped')),
+    input_data JSONB NOT NULL,
+    output_data JSONB,
+    error_message TEXT,
+    started_at TIMESTAMPTZ,
+    completed_at TIMESTAMPTZ,
+    PRIMARY KEY (batch_id, child_index)
+);
+
+ALTER TABLE workflow_batches ENABLE ROW LEVEL SECURITY;
+ALTER TABLE workflow_batch_children ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY tenant_isolation_workflow_batches ON workflow_batches
+    FOR ALL TO PUBLIC
+    USING (tenant_id = current_setting('app.current_tenant_id', true)::UUID);
+
+CREATE POLICY tenant_isolation_workflow_batch_children ON workflow_batch_children
+    FOR ALL TO PUBLIC
+    USING (tenant_id = current_setting('app.current_tenant_id', true)::UUID);
+
+CREATE INDEX IF NOT EXISTS idx_workflow_batches_tenant_created ON workflow_batches(tenant_id, created_at DESC);
+CREATE INDEX IF NOT EXISTS idx_workflow_batch_children_status ON workflow_batch_children(batch_id, status);
+
+CREATE TRIGGER update_workflow_batches_updated_at BEFORE UPDATE ON workflow_batches FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
@@ -0,0 +1,73 @@
+-- Workflow versions
+-- Registered code versions per workflow type, one of them the default new
+-- runs start on. Versions are global rather than per tenant: the same
+-- worker code runs every tenant's workflows.
+
+CREATE TABLE IF NOT EXISTS workflow_versions (
+    workflow_type VARCHAR(100) NOT NULL,
+    version VARCHAR(50) NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'active' CHECK (status IN ('beta', 'active', 'deprecated', 'sunset')),
+    is_default BOOLEAN NOT NULL DEFAULT false,
+    description TEXT NOT NULL DEFAULT '',
+    input_schema JSONB NOT NULL DEFAULT '{}',
+    breaking_changes JSONB NOT NULL DEFAULT '[]',
+    migration_notes TEXT,
+    registered_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    deprecated_at TIMESTAMPTZ,
+    deprecation_reason TEXT,
+    sunset_date TIMESTAMPTZ,
+    migration_deadline TIMESTAMPTZ,
+    PRIMARY KEY (workflow_type, version)
+);
+
+CREATE UNIQUE INDEX IF NOT EXISTS idx_workflow_versions_default ON workflow_versions(workflow_type) WHERE is_default;
+
+-- The version each run started on; runs of types without a registered
+-- version keep NULL
+ALTER TABLE workflow_executions ADD COLUMN IF NOT EXISTS workflow_version VARCHAR(50);
+
+CREATE INDEX IF NOT EXISTS idx_workflow_executions_running_version
+    ON workflow_executions(workflow_type, workflow_version, started_at)
+    WHERE status = 'running';
+
+CREATE OR REPLACE FUNCTION set_workflow_execution_version()
+RETURNS TRIGGER AS $$
+BEGIN
+    IF NEW.workflow_version IS NULL THEN
+        SELECT version INTO NEW.workflow_version
+        FROM workflow_versions
+        WHERE workflow_type = NEW.workflow_type AND is_default;
+    END IF;
+    RETURN NEW;
+END;
+$$ LANGUAGE plpgsql;
+
+CREATE TRIGGER set_workflow_executions_version BEFORE INSERT ON workflow_executions FOR EACH ROW EXECUTE FUNCTION set_workflow_execution_version();
+
+-- Rollouts from one version to another. A patch rollout re-tags running
+-- executions and keeps their ids so it can be rolled back; a drain waits
+-- for them to finish.
+CREATE TABLE IF NOT EXISTS workflow_version_rollouts (
+    id UUID PRIMARY KEY,
+    workflow_type VARCHAR(100) NOT NULL,
+    from_version VARCHAR(50) NOT NULL,
+    to_version VARCHAR(50) NOT NULL,
+    strategy VARCHAR(20) NOT NULL CHECK (strategy IN ('patch', 'drain')),
+    status VARCHAR(20) NOT NULL DEFAULT 'running' CHECK (status IN ('pending', 'running', 'completed', 'failed', 'rolled_back')),
+    dry_run BOOLEAN NOT NULL DEFAULT false,
+    rollback_enabled BOOLEAN NOT NULL DEFAULT true,
+    batch_size INTEGER NOT NULL CHECK (batch_size > 0),
+    total_workflows INTEGER NOT NULL DEFAULT 0,
+    migrated_workflow_ids JSONB NOT NULL DEFAULT '[]',
+    failed_migrations INTEGER NOT NULL DEFAULT 0,
+    remaining_workflows INTEGER NOT NULL DEFAULT 0,
+    errors JSONB NOT NULL DEFAULT '[]',
+    rollback_reason TEXT,
+    started_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    completed_at TIMESTAMPTZ,
+    FOREIGN KEY (workflow_type, from_version) REFERENCES workflow_versions(workflow_type, version),
+    FOREIGN KEY (workflow_type, to_version) REFERENCES workflow_versions(workflow_type, version)
+);
+
+CREATE INDEX IF NOT EXISTS idx_workflow_version_rollouts_type ON workflow_version_rollouts(workflow_type, started_at DESC);
@@ -0,0 +1,63 @@
+-- Workflow SLAs
+-- Per-tenant service levels for a workflow type: a maximum run duration
+-- and/or a maximum failure rate over a sliding window. Breaches are kept
+-- so each one is notified once, and failure-rate breaches stay open until
+-- the rate recovers.
+
+CREATE TABLE IF NOT EXISTS workflow_slas (
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    workflow_type VARCHAR(100) NOT NULL,
+    max_duration_seconds BIGINT CHECK (max_duration_seconds > 0),
+    max_failure_rate DOUBLE PRECISION CHECK (max_failure_rate >= 0 AND max_failure_rate <= 100),
+    window_minutes INTEGER NOT NULL DEFAULT 60 CHECK (window_minutes > 0),
+    min_runs INTEGER NOT NULL DEFAULT 10 CHECK (min_runs > 0),
+    webhook_url TEXT,
+    webhook_secret TEXT,
+    is_enabled BOOLEAN NOT NULL DEFAULT true,
+    created_by UUID NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (tenant_id, workflow_type),
+    CHECK (max_duration_seconds IS NOT NULL OR max_failure_rate IS NOT NULL)
+);
+
+CREATE TABLE IF NOT EXISTS workflow_sla_breaches (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    workflow_type VARCHAR(100) NOT NULL,
+    kind VARCHAR(20) NOT NULL CHECK (kind IN ('duration', 'failure_rate')),
+    -- The overrunning run; NULL for failure-rate breaches
+    workflow_id VARCHAR(255),
+    threshold DOUBLE PRECISION NOT NULL,
+    observed DOUBLE PRECISION NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'resolved')),
+    detected_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    resolved_at TIMESTAMPTZ,
+    notified_at TIMESTAMPTZ,
+    notification_error TEXT
+);
+
+-- Workflow run costs double as the record of finished inline runs
+ALTER TABLE workflow_run_costs ADD COLUMN IF NOT EXISTS started_at TIMESTAMPTZ;
+ALTER TABLE workflow_run_costs ADD COLUMN IF NOT EXISTS succeeded BOOLEAN;
+
+ALTER TABLE workflow_slas ENABLE ROW LEVEL SECURITY;
+ALTER TABLE workflow_sla_breaches ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY tenant_isolation_workflow_slas ON workflow_slas
+    FOR ALL TO PUBLIC
+    USING (tenant_id = current_setting('app.current_tenant_id', true)::UUID);
+
+CREATE POLICY tenant_isolation_workflow_sla_breaches ON workflow_sla_breaches
+    FOR ALL TO PUBLIC
+    USING (tenant_id = current_setting('app.current_tenant_id', true)::UUID);
+
+-- One breach per overrunning run, and one open failure-rate breach per type
+CREATE UNIQUE INDEX IF NOT EXISTS idx_workflow_sla_breaches_run
+    ON workflow_sla_breaches(tenant_id, workflow_type, workflow_id) WHERE kind = 'duration';
+CREATE UNIQUE INDEX IF NOT EXISTS idx_workflow_sla_breaches_open_rate
+    ON workflow_sla_breaches(tenant_id, workflow_type) WHERE kind = 'failure_rate' AND status = 'open';
+CREATE INDEX IF NOT EXISTS idx_workflow_sla_breaches_tenant_detected ON workflow_sla_breaches(tenant_id, detected_at DESC);
+CREATE INDEX IF NOT EXISTS idx_workflow_run_costs_type_recorded ON workflow_run_costs(tenant_id, workflow_type, recorded_at);
+
+CREATE TRIGGER update_workflow_slas_updated_at BEFORE UPDATE ON workflow_slas FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
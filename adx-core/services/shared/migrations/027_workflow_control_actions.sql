@@ -0,0 +1,27 @@
+-- Workflow control actions
+-- Audit trail of the pause, resume and cancel requests made for workflow
+-- runs. A Temporal run's paused state is read from its latest pause or
+-- resume entry.
+
+CREATE TABLE IF NOT EXISTS workflow_control_actions (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    workflow_id VARCHAR(255) NOT NULL,
+    run_id VARCHAR(255),
+    workflow_type VARCHAR(100) NOT NULL,
+    action VARCHAR(20) NOT NULL CHECK (action IN ('pause', 'resume', 'cancel')),
+    reason TEXT,
+    requested_by UUID NOT NULL,
+    -- Whether the run executes inside the service rather than on Temporal
+    inline_run BOOLEAN NOT NULL,
+    requested_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+ALTER TABLE workflow_control_actions ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY tenant_isolation_workflow_control_actions ON workflow_control_actions
+    FOR ALL TO PUBLIC
+    USING (tenant_id = current_setting('app.current_tenant_id', true)::UUID);
+
+CREATE INDEX IF NOT EXISTS idx_workflow_control_actions_workflow
+    ON workflow_control_actions(tenant_id, workflow_id, requested_at DESC);
@@ -0,0 +1,79 @@
+-- Workflow triggers
+-- Per-tenant rules that start a workflow when a platform event arrives, the
+-- events received and the workflows each rule started for them
+
+CREATE TABLE IF NOT EXISTS workflow_triggers (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    name VARCHAR(255) NOT NULL,
+    description TEXT,
+    event_type VARCHAR(100) NOT NULL, -- exact type, or a prefix ending in '.*'
+    filter JSONB NOT NULL DEFAULT '{}', -- event data paths and the values they must hold
+    workflow_type VARCHAR(100) NOT NULL,
+    input_template JSONB NOT NULL DEFAULT '{}',
+    enabled BOOLEAN NOT NULL DEFAULT TRUE,
+    created_by UUID REFERENCES users(id) ON DELETE SET NULL,
+    last_fired_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(tenant_id, name)
+);
+
+CREATE TABLE IF NOT EXISTS workflow_trigger_events (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    event_type VARCHAR(100) NOT NULL,
+    source VARCHAR(100) NOT NULL,
+    subject VARCHAR(255),
+    data JSONB NOT NULL DEFAULT '{}',
+    status VARCHAR(20) NOT NULL DEFAULT 'pending' CHECK (status IN ('pending', 'dispatched', 'failed')),
+    attempts INTEGER NOT NULL DEFAULT 0 CHECK (attempts >= 0),
+    error TEXT,
+    occurred_at TIMESTAMPTZ NOT NULL,
+    received_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    dispatched_at TIMESTAMPTZ
+);
+
+CREATE TABLE IF NOT EXISTS workflow_trigger_firings (
+    id UUID PRIMARY KEY,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    trigger_id UUID NOT NULL REFERENCES workflow_triggers(id) ON DELETE CASCADE,
+    event_id UUID NOT NULL REFERENCES workflow_trigger_events(id) ON DELETE CASCADE,
+    workflow_id VARCHAR(255),
+    status VARCHAR(20) NOT NULL CHECK (status IN ('started', 'failed')),
+    error TEXT,
+    fired_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    UNIQUE(trigger_id, event_id)
+);
+
+-- Events waiting for the dispatcher. Holds ids only and has no row level
+-- security, so the dispatcher can claim events of every tenant.
+CREATE TABLE IF NOT EXISTS workflow_trigger_dispatch_queue (
+    event_id UUID PRIMARY KEY REFERENCES workflow_trigger_events(id) ON DELETE CASCADE,
+    tenant_id UUID NOT NULL REFERENCES tenants(id) ON DELETE CASCADE,
+    available_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    attempts INTEGER NOT NULL DEFAULT 0 CHECK (attempts >= 0)
+);
+
+ALTER TABLE workflow_triggers ENABLE ROW LEVEL SECURITY;
+ALTER TABLE workflow_trigger_events ENABLE ROW LEVEL SECURITY;
+ALTER TABLE workflow_trigger_firings ENABLE ROW LEVEL SECURITY;
+
+CREATE POLICY tenant_isolation_workflow_triggers ON workflow_triggers
+    FOR ALL TO PUBLIC
+    USING (tenant_id = current_setting('app.current_tenant_id', true)::UUID);
+
+CREATE POLICY tenant_isolation_workflow_trigger_events ON workflow_trigger_events
+    FOR ALL TO PUBLIC
+    USING (tenant_id = current_setting('app.current_tenant_id', true)::UUID);
+
+CREATE POLICY tenant_isolation_workflow_trigger_firings ON workflow_trigger_firings
+    FOR ALL TO PUBLIC
+    USING (tenant_id = current_setting('app.current_tenant_id', true)::UUID);
+
+CREATE INDEX IF NOT EXISTS idx_workflow_triggers_tenant_event ON workflow_triggers(tenant_id, enabled, event_type);
+CREATE INDEX IF NOT EXISTS idx_workflow_trigger_events_tenant ON workflow_trigger_events(tenant_id, received_at DESC);
+CREATE INDEX IF NOT EXISTS idx_workflow_trigger_firings_trigger ON workflow_trigger_firings(trigger_id, fired_at DESC);
+CREATE INDEX IF NOT EXISTS idx_workflow_trigger_dispatch_queue_available ON workflow_trigger_dispatch_queue(available_at);
+
+CREATE TRIGGER update_workflow_triggers_updated_at BEFORE UPDATE ON workflow_triggers FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
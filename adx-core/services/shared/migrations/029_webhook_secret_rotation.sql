@@ -0,0 +1,9 @@
+-- Webhook secret rotation
+-- After a rotation the replaced secret stays valid until previous_secret_expires_at,
+-- and deliveries carry a signature for each valid secret so receivers can
+-- switch over at their own pace.
+
+ALTER TABLE tenant_lifecycle_webhooks
+    ADD COLUMN IF NOT EXISTS previous_secret TEXT,
+    ADD COLUMN IF NOT EXISTS previous_secret_expires_at TIMESTAMPTZ,
+    ADD COLUMN IF NOT EXISTS secret_rotated_at TIMESTAMPTZ;
@@ -0,0 +1,401 @@
+// Platform audit events
+//
+// Services report audit-worthy actions as `AuditEvent`s through an
+// `AuditEmitter`. Emitting only queues the event; a background task ships
+// queued events to security-service in batches, so auditing never holds up
+// a request. security-service chains each tenant's events by hash, which
+// makes later tampering with the stored trail detectable.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+use uuid::Uuid;
+
+use crate::{Result, ServiceError};
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditCategory {
+    Authentication,
+    Authorization,
+    DataAccess,
+    DataModification,
+    SystemAccess,
+    Configuration,
+    Security,
+    Compliance,
+    Privacy,
+    Administrative,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuditOutcome {
+    Success,
+    Failure,
+    Warning,
+    Error,
+}
+
+/// One audit-worthy action, as reported by the service it happened in
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct AuditEvent {
+    pub id: Uuid,
+    pub tenant_id: String,
+    /// Service that reported the event, e.g. "file-service"
+    pub service: String,
+    pub category: AuditCategory,
+    pub action: String,
+    pub resource_type: String,
+    pub resource_id: Option<String>,
+    pub outcome: AuditOutcome,
+    /// User, or service account, that performed the action
+    pub actor_id: Option<String>,
+    pub session_id: Option<String>,
+    pub ip_address: Option<String>,
+    pub user_agent: Option<String>,
+    pub request_id: Option<String>,
+    #[serde(default)]
+    pub details: Value,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl AuditEvent {
+    pub fn new(
+        tenant_id: impl Into<String>,
+        service: impl Into<String>,
+        category: AuditCategory,
+        action: impl Into<String>,
+        resource_type: impl Into<String>,
+    ) -> Self {
+        Self {
+            id: Uuid::new_v4(),
+            tenant_id: tenant_id.into(),
+            service: service.into(),
+            category,
+            action: action.into(),
+            resource_type: resource_type.into(),
+            resource_id: None,
+            outcome: AuditOutcome::Success,
+            actor_id: None,
+            session_id: None,
+            ip_address: None,
+            user_agent: None,
+            request_id: None,
+            details: Value::Null,
+            occurred_at: Utc::now(),
+        }
+    }
+
+    pub fn resource_id(mut self, resource_id: impl Into<String>) -> Self {
+        self.resource_id = Some(resource_id.into());
+        self
+    }
+
+    pub fn outcome(mut self, outcome: AuditOutcome) -> Self {
+        self.outcome = outcome;
+        self
+    }
+
+    pub fn actor(mut self, actor_id: impl Into<String>) -> Self {
+        self.actor_id = Some(actor_id.into());
+        self
+    }
+
+    pub fn session(mut self, session_id: impl Into<String>) -> Self {
+        self.session_id = Some(session_id.into());
+        self
+    }
+
+    pub fn client(mut self, ip_address: Option<String>, user_agent: Option<String>) -> Self {
+        self.ip_address = ip_address;
+        self.user_agent = user_agent;
+        self
+    }
+
+    pub fn request_id(mut self, request_id: impl Into<String>) -> Self {
+        self.request_id = Some(request_id.into());
+        self
+    }
+
+    pub fn details(mut self, details: Value) -> Self {
+        self.details = details;
+        self
+    }
+}
+
+/// Where emitted events are delivered
+#[async_trait]
+pub trait AuditSink: Send + Sync {
+    async fn send(&self, events: &[AuditEvent]) -> Result<()>;
+}
+
+#[derive(Serialize)]
+struct IngestRequest<'a> {
+    events: &'a [AuditEvent],
+}
+
+/// Delivers events to security-service's ingestion endpoint
+pub struct HttpAuditSink {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl HttpAuditSink {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl AuditSink for HttpAuditSink {
+    async fn send(&self, events: &[AuditEvent]) -> Result<()> {
+        let url = format!("{}/api/v1/audit/events", self.base_url);
+
+        let response = self
+            .client
+            .post(&url)
+            .json(&IngestRequest { events })
+            .send()
+            .await
+            .map_err(|e| ServiceError::ExternalService(format!("Audit delivery failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(ServiceError::ExternalService(format!(
+                "Audit delivery of {} events returned {}",
+                events.len(),
+                response.status()
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct AuditEmitterConfig {
+    /// Events sent in one request
+    pub batch_size: usize,
+    /// Longest an event waits in the queue before its batch is sent
+    pub flush_interval: Duration,
+    /// Events held while security-service is slow or unreachable; beyond
+    /// this new events are dropped rather than blocking the caller
+    pub queue_capacity: usize,
+    /// Attempts at delivering a batch before it is given up on
+    pub max_attempts: u32,
+    /// Wait before the first retry, doubling with each further one
+    pub retry_backoff: Duration,
+}
+
+impl Default for AuditEmitterConfig {
+    fn default() -> Self {
+        Self {
+            batch_size: 100,
+            flush_interval: Duration::from_secs(5),
+            queue_capacity: 10_000,
+            max_attempts: 3,
+            retry_backoff: Duration::from_millis(200),
+        }
+    }
+}
+
+/// Queues audit events and ships them in the background
+#[derive(Clone)]
+pub struct AuditEmitter {
+    sender: mpsc::Sender<AuditEvent>,
+}
+
+impl AuditEmitter {
+    /// Start the background task delivering to `sink`. Must be called from
+    /// within a Tokio runtime.
+    pub fn spawn(sink: Arc<dyn AuditSink>, config: AuditEmitterConfig) -> Self {
+        let (sender, receiver) = mpsc::channel(config.queue_capacity.max(1));
+        tokio::spawn(run_delivery(receiver, sink, config));
+        Self { sender }
+    }
+
+    /// Emitter delivering to security-service at `base_url`
+    pub fn from_url(base_url: &str) -> Self {
+        Self::spawn(
+            Arc::new(HttpAuditSink::new(base_url)),
+            AuditEmitterConfig::default(),
+        )
+    }
+
+    /// Queue an event. Returns false when it was dropped because the queue
+    /// is full or delivery has stopped.
+    pub fn emit(&self, event: AuditEvent) -> bool {
+        match self.sender.try_send(event) {
+            Ok(()) => true,
+            Err(mpsc::error::TrySendError::Full(event)) => {
+                tracing::warn!(tenant_id = %event.tenant_id, action = %event.action, "Audit queue full, dropping event");
+                false
+            }
+            Err(mpsc::error::TrySendError::Closed(event)) => {
+                tracing::warn!(tenant_id = %event.tenant_id, action = %event.action, "Audit delivery stopped, dropping event");
+                false
+            }
+        }
+    }
+}
+
+async fn run_delivery(
+    mut receiver: mpsc::Receiver<AuditEvent>,
+    sink: Arc<dyn AuditSink>,
+    config: AuditEmitterConfig,
+) {
+    let batch_size = config.batch_size.max(1);
+    let mut batch = Vec::with_capacity(batch_size);
+    let mut ticker = tokio::time::interval(config.flush_interval);
+
+    loop {
+        tokio::select! {
+            event = receiver.recv() => match event {
+                Some(event) => {
+                    batch.push(event);
+                    if batch.len() >= batch_size {
+                        deliver(sink.as_ref(), &config, &mut batch).await;
+                    }
+                }
+                None => {
+                    // Every emitter is gone; send what is left and stop
+                    deliver(sink.as_ref(), &config, &mut batch).await;
+                    return;
+                }
+            },
+            _ = ticker.tick() => deliver(sink.as_ref(), &config, &mut batch).await,
+        }
+    }
+}
+
+async fn deliver(sink: &dyn AuditSink, config: &AuditEmitterConfig, batch: &mut Vec<AuditEvent>) {
+    if batch.is_empty() {
+        return;
+    }
+
+    let mut backoff = config.retry_backoff;
+    for attempt in 1..=config.max_attempts.max(1) {
+        match sink.send(batch).await {
+            Ok(()) => {
+                batch.clear();
+                return;
+            }
+            Err(e) if attempt < config.max_attempts => {
+                tracing::warn!(attempt = attempt, error = %e, "Audit delivery failed, retrying");
+                tokio::time::sleep(backoff).await;
+                backoff *= 2;
+            }
+            Err(e) => {
+                tracing::error!(count = batch.len(), error = %e, "Audit delivery failed, dropping batch");
+            }
+        }
+    }
+    batch.clear();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use tokio::sync::Mutex;
+
+    struct RecordingSink {
+        batches: Mutex<Vec<Vec<AuditEvent>>>,
+        failures_left: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl AuditSink for RecordingSink {
+        async fn send(&self, events: &[AuditEvent]) -> Result<()> {
+            if self
+                .failures_left
+                .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |left| {
+                    left.checked_sub(1)
+                })
+                .is_ok()
+            {
+                return Err(ServiceError::ExternalService("unavailable".to_string()));
+            }
+            self.batches.lock().await.push(events.to_vec());
+            Ok(())
+        }
+    }
+
+    fn sink(failures: usize) -> Arc<RecordingSink> {
+        Arc::new(RecordingSink {
+            batches: Mutex::new(Vec::new()),
+            failures_left: AtomicUsize::new(failures),
+        })
+    }
+
+    fn config(batch_size: usize) -> AuditEmitterConfig {
+        AuditEmitterConfig {
+            batch_size,
+            flush_interval: Duration::from_millis(20),
+            queue_capacity: 100,
+            max_attempts: 3,
+            retry_backoff: Duration::from_millis(1),
+        }
+    }
+
+    fn event(action: &str) -> AuditEvent {
+        AuditEvent::new(
+            "tenant-1",
+            "file-service",
+            AuditCategory::DataModification,
+            action,
+            "file",
+        )
+        .resource_id("file-1")
+        .actor("user-1")
+    }
+
+    async fn delivered(sink: &RecordingSink) -> Vec<Vec<AuditEvent>> {
+        tokio::time::sleep(Duration::from_millis(100)).await;
+        sink.batches.lock().await.clone()
+    }
+
+    #[tokio::test]
+    async fn test_events_are_sent_in_batches() {
+        let sink = sink(0);
+        let emitter = AuditEmitter::spawn(sink.clone(), config(2));
+
+        for action in ["upload", "rename", "delete"] {
+            assert!(emitter.emit(event(action)));
+        }
+
+        let batches = delivered(&sink).await;
+        assert_eq!(batches.len(), 2);
+        assert_eq!(batches[0].len(), 2);
+        // The last event goes out on the flush interval
+        assert_eq!(batches[1][0].action, "delete");
+    }
+
+    #[tokio::test]
+    async fn test_failed_delivery_is_retried() {
+        let sink = sink(2);
+        let emitter = AuditEmitter::spawn(sink.clone(), config(1));
+
+        emitter.emit(event("upload"));
+
+        let batches = delivered(&sink).await;
+        assert_eq!(batches.len(), 1);
+        assert_eq!(batches[0][0].resource_id.as_deref(), Some("file-1"));
+    }
+
+    #[test]
+    fn test_event_wire_format() {
+        let event = event("upload").outcome(AuditOutcome::Failure);
+        let json = serde_json::to_value(&event).unwrap();
+
+        assert_eq!(json["category"], "data_modification");
+        assert_eq!(json["outcome"], "failure");
+        assert_eq!(serde_json::from_value::<AuditEvent>(json).unwrap(), event);
+    }
+}
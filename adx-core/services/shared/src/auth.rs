@@ -1,8 +1,9 @@
 // Authentication utilities
 
-use jsonwebtoken::{decode, encode, DecodingKey, EncodingKey, Header, Validation};
+use jsonwebtoken::{decode, decode_header, encode, DecodingKey, EncodingKey, Header, Validation};
 use serde::{Deserialize, Serialize};
 use chrono::{Duration, Utc};
+use crate::signing_keys::SigningKeySet;
 use crate::{Result, ServiceError};
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -17,16 +18,39 @@ pub struct Claims {
 
 pub struct AuthManager {
     encoding_key: EncodingKey,
-    decoding_key: DecodingKey,
+    /// Key id stamped into issued tokens; None for a single static secret
+    signing_kid: Option<String>,
+    decoding_keys: Vec<(Option<String>, DecodingKey)>,
 }
 
 impl AuthManager {
     pub fn new(secret: &str) -> Self {
         Self {
             encoding_key: EncodingKey::from_secret(secret.as_ref()),
-            decoding_key: DecodingKey::from_secret(secret.as_ref()),
+            signing_kid: None,
+            decoding_keys: vec![(None, DecodingKey::from_secret(secret.as_ref()))],
         }
     }
+
+    /// Sign with the set's active key and accept tokens from every key still
+    /// valid, so tokens issued before a rotation keep working through it
+    pub fn from_key_set(key_set: &SigningKeySet) -> Result<Self> {
+        let active = key_set.active().ok_or_else(|| {
+            ServiceError::Configuration(format!("Key set {}/{} has no active key", key_set.purpose, key_set.owner_id))
+        })?;
+        let now = Utc::now();
+
+        Ok(Self {
+            encoding_key: EncodingKey::from_secret(active.secret.as_ref()),
+            signing_kid: Some(active.kid.clone()),
+            decoding_keys: key_set
+                .keys
+                .iter()
+                .filter(|key| key.is_valid_at(now))
+                .map(|key| (Some(key.kid.clone()), DecodingKey::from_secret(key.secret.as_ref())))
+                .collect(),
+        })
+    }
     
     pub fn generate_token(&self, user_id: &str, tenant_id: &str, email: &str, roles: Vec<String>) -> Result<String> {
         let now = Utc::now();
@@ -39,14 +63,34 @@ impl AuthManager {
             iat: now.timestamp(),
         };
         
-        encode(&Header::default(), &claims, &self.encoding_key)
+        let mut header = Header::default();
+        header.kid = self.signing_kid.clone();
+
+        encode(&header, &claims, &self.encoding_key)
             .map_err(|e| ServiceError::Authentication(e.to_string()))
     }
     
     pub fn validate_token(&self, token: &str) -> Result<Claims> {
-        decode::<Claims>(token, &self.decoding_key, &Validation::default())
-            .map(|data| data.claims)
-            .map_err(|e| ServiceError::Authentication(e.to_string()))
+        let kid = decode_header(token)
+            .map_err(|e| ServiceError::Authentication(e.to_string()))?
+            .kid;
+
+        // A token naming its key is checked against that key only
+        let candidates: Vec<&DecodingKey> = self
+            .decoding_keys
+            .iter()
+            .filter(|(key_kid, _)| kid.is_none() || key_kid.is_none() || *key_kid == kid)
+            .map(|(_, key)| key)
+            .collect();
+
+        let mut last_error = ServiceError::Authentication("No key matches the token's key id".to_string());
+        for key in candidates {
+            match decode::<Claims>(token, key, &Validation::default()) {
+                Ok(data) => return Ok(data.claims),
+                Err(e) => last_error = ServiceError::Authentication(e.to_string()),
+            }
+        }
+        Err(last_error)
     }
     
     pub fn hash_password(&self, password: &str) -> Result<String> {
@@ -103,6 +147,52 @@ mod tests {
         assert_eq!(claims.roles, vec!["user", "admin"]);
     }
 
+    #[test]
+    fn test_tokens_survive_key_rotation() {
+        use crate::signing_keys::{SigningKey, SigningKeyStatus};
+
+        fn key(kid: &str, status: SigningKeyStatus) -> SigningKey {
+            SigningKey {
+                kid: kid.to_string(),
+                status,
+                secret: format!("secret-{}", kid),
+                activated_at: None,
+                expires_at: None,
+            }
+        }
+        fn key_set(keys: Vec<SigningKey>) -> SigningKeySet {
+            SigningKeySet {
+                purpose: "jwt_signing".to_string(),
+                owner_id: "platform".to_string(),
+                keys,
+                generated_at: Utc::now(),
+            }
+        }
+
+        let before = AuthManager::from_key_set(&key_set(vec![
+            key("k1", SigningKeyStatus::Active),
+            key("k2", SigningKeyStatus::Pending),
+        ]))
+        .unwrap();
+        let old_token = before.generate_token("user123", "tenant456", "user@example.com", vec![]).unwrap();
+        assert_eq!(decode_header(&old_token).unwrap().kid.as_deref(), Some("k1"));
+
+        let after = AuthManager::from_key_set(&key_set(vec![
+            key("k2", SigningKeyStatus::Active),
+            key("k1", SigningKeyStatus::Retiring),
+        ]))
+        .unwrap();
+        let new_token = after.generate_token("user123", "tenant456", "user@example.com", vec![]).unwrap();
+
+        // Each side of the rotation accepts the other's tokens
+        assert_eq!(after.validate_token(&old_token).unwrap().sub, "user123");
+        assert_eq!(before.validate_token(&new_token).unwrap().sub, "user123");
+
+        let retired = AuthManager::from_key_set(&key_set(vec![key("k2", SigningKeyStatus::Active)])).unwrap();
+        assert!(retired.validate_token(&old_token).is_err());
+        assert!(retired.validate_token(&new_token).is_ok());
+    }
+
     #[test]
     fn test_invalid_token() {
         let auth = get_test_auth_manager();
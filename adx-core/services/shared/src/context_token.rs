@@ -0,0 +1,246 @@
+// Signed tenant context tokens
+//
+// When a user switches tenant, tenant-service issues a short-lived token that
+// carries the resolved tenant context (tier, role, permissions, features).
+// The api-gateway and service middleware verify it locally with the platform
+// secret instead of calling tenant-service on every request. The current token
+// for each user and tenant is cached in Redis so other services can look it
+// up, and the cache is dropped when a tenant's access changes. A token that is
+// already out keeps working until it expires, so the TTL bounds how stale a
+// context can be.
+
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use jsonwebtoken::{decode, encode, Algorithm, DecodingKey, EncodingKey, Header, Validation};
+use redis::AsyncCommands;
+use serde::{Deserialize, Serialize};
+
+use crate::types::{SessionId, SubscriptionTier, TenantId, UserId};
+use crate::{Result, ServiceError};
+
+/// Request header carrying the tenant context token
+pub const TENANT_CONTEXT_HEADER: &str = "X-Tenant-Context";
+/// Audience claim that keeps context tokens and login tokens from being swapped
+pub const TENANT_CONTEXT_AUDIENCE: &str = "adx-tenant-context";
+pub const DEFAULT_CONTEXT_TOKEN_TTL_SECS: i64 = 300;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TenantContextClaims {
+    /// User the context was issued to
+    pub sub: UserId,
+    pub tenant_id: TenantId,
+    pub tenant_name: String,
+    pub session_id: SessionId,
+    pub subscription_tier: SubscriptionTier,
+    pub role: String,
+    pub permissions: Vec<String>,
+    /// Enabled feature flags
+    pub features: Vec<String>,
+    pub aud: String,
+    pub iat: i64,
+    pub exp: i64,
+}
+
+impl TenantContextClaims {
+    pub fn expires_at(&self) -> DateTime<Utc> {
+        Utc.timestamp_opt(self.exp, 0).single().unwrap_or_else(Utc::now)
+    }
+
+    pub fn has_permission(&self, permission: &str) -> bool {
+        self.permissions.iter().any(|p| p == permission || p == "*")
+    }
+}
+
+/// What goes into a context token; timestamps and audience are set on issue
+#[derive(Debug, Clone)]
+pub struct TenantContextGrant {
+    pub user_id: UserId,
+    pub tenant_id: TenantId,
+    pub tenant_name: String,
+    pub session_id: SessionId,
+    pub subscription_tier: SubscriptionTier,
+    pub role: String,
+    pub permissions: Vec<String>,
+    pub features: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssuedContextToken {
+    pub token: String,
+    pub expires_at: DateTime<Utc>,
+}
+
+pub struct TenantContextSigner {
+    encoding_key: EncodingKey,
+    decoding_key: DecodingKey,
+    ttl: Duration,
+}
+
+impl TenantContextSigner {
+    pub fn new(secret: &str) -> Self {
+        Self {
+            encoding_key: EncodingKey::from_secret(secret.as_ref()),
+            decoding_key: DecodingKey::from_secret(secret.as_ref()),
+            ttl: Duration::seconds(DEFAULT_CONTEXT_TOKEN_TTL_SECS),
+        }
+    }
+
+    pub fn with_ttl(mut self, ttl_secs: i64) -> Self {
+        self.ttl = Duration::seconds(ttl_secs);
+        self
+    }
+
+    pub fn ttl_secs(&self) -> i64 {
+        self.ttl.num_seconds()
+    }
+
+    pub fn issue(&self, grant: TenantContextGrant) -> Result<IssuedContextToken> {
+        let now = Utc::now();
+        let claims = TenantContextClaims {
+            sub: grant.user_id,
+            tenant_id: grant.tenant_id,
+            tenant_name: grant.tenant_name,
+            session_id: grant.session_id,
+            subscription_tier: grant.subscription_tier,
+            role: grant.role,
+            permissions: grant.permissions,
+            features: grant.features,
+            aud: TENANT_CONTEXT_AUDIENCE.to_string(),
+            iat: now.timestamp(),
+            exp: (now + self.ttl).timestamp(),
+        };
+
+        let token = encode(&Header::new(Algorithm::HS256), &claims, &self.encoding_key)
+            .map_err(|e| ServiceError::Authentication(e.to_string()))?;
+
+        Ok(IssuedContextToken {
+            token,
+            expires_at: claims.expires_at(),
+        })
+    }
+
+    /// Check signature, audience and expiry without any network call
+    pub fn verify(&self, token: &str) -> Result<TenantContextClaims> {
+        let mut validation = Validation::new(Algorithm::HS256);
+        validation.set_audience(&[TENANT_CONTEXT_AUDIENCE]);
+        validation.leeway = 0;
+
+        decode::<TenantContextClaims>(token, &self.decoding_key, &validation)
+            .map(|data| data.claims)
+            .map_err(|e| ServiceError::Authentication(format!("Invalid tenant context token: {}", e)))
+    }
+}
+
+fn token_key(user_id: &str, tenant_id: &str) -> String {
+    format!("tenant_context:{}:{}", tenant_id, user_id)
+}
+
+fn tenant_index_key(tenant_id: &str) -> String {
+    format!("tenant_context_index:{}", tenant_id)
+}
+
+/// Redis cache of issued context tokens, keyed by tenant and user
+pub struct TenantContextCache {
+    client: redis::Client,
+}
+
+impl TenantContextCache {
+    pub fn new(redis_url: &str) -> Result<Self> {
+        Ok(Self {
+            client: redis::Client::open(redis_url)?,
+        })
+    }
+
+    pub async fn get(&self, user_id: &str, tenant_id: &str) -> Result<Option<String>> {
+        let mut conn = self.client.get_async_connection().await?;
+        Ok(conn.get(token_key(user_id, tenant_id)).await?)
+    }
+
+    pub async fn put(&self, user_id: &str, tenant_id: &str, token: &IssuedContextToken) -> Result<()> {
+        let ttl = (token.expires_at - Utc::now()).num_seconds();
+        if ttl <= 0 {
+            return Ok(());
+        }
+
+        let mut conn = self.client.get_async_connection().await?;
+        let index_key = tenant_index_key(tenant_id);
+        redis::pipe()
+            .set_ex(token_key(user_id, tenant_id), &token.token, ttl as u64)
+            .sadd(&index_key, user_id)
+            .expire(&index_key, DEFAULT_CONTEXT_TOKEN_TTL_SECS.max(ttl))
+            .query_async::<_, ()>(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn invalidate_user(&self, user_id: &str, tenant_id: &str) -> Result<()> {
+        let mut conn = self.client.get_async_connection().await?;
+        redis::pipe()
+            .del(token_key(user_id, tenant_id))
+            .srem(tenant_index_key(tenant_id), user_id)
+            .query_async::<_, ()>(&mut conn)
+            .await?;
+        Ok(())
+    }
+
+    /// Drop every cached token for a tenant, e.g. after suspension or a tier change
+    pub async fn invalidate_tenant(&self, tenant_id: &str) -> Result<usize> {
+        let mut conn = self.client.get_async_connection().await?;
+        let index_key = tenant_index_key(tenant_id);
+        let user_ids: Vec<String> = conn.smembers(&index_key).await?;
+
+        let mut pipe = redis::pipe();
+        for user_id in &user_ids {
+            pipe.del(token_key(user_id, tenant_id));
+        }
+        pipe.del(&index_key);
+        pipe.query_async::<_, ()>(&mut conn).await?;
+
+        Ok(user_ids.len())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn grant() -> TenantContextGrant {
+        TenantContextGrant {
+            user_id: "user-1".to_string(),
+            tenant_id: "tenant-1".to_string(),
+            tenant_name: "Acme".to_string(),
+            session_id: "session-1".to_string(),
+            subscription_tier: SubscriptionTier::Professional,
+            role: "Admin".to_string(),
+            permissions: vec!["tenant:read".to_string()],
+            features: vec!["workflows".to_string()],
+        }
+    }
+
+    #[test]
+    fn test_context_token_round_trip() {
+        let signer = TenantContextSigner::new("test-secret-key");
+        let issued = signer.issue(grant()).unwrap();
+
+        let claims = signer.verify(&issued.token).unwrap();
+        assert_eq!(claims.tenant_id, "tenant-1");
+        assert_eq!(claims.aud, TENANT_CONTEXT_AUDIENCE);
+        assert_eq!(claims.exp - claims.iat, DEFAULT_CONTEXT_TOKEN_TTL_SECS);
+        assert!(claims.has_permission("tenant:read"));
+        assert!(!claims.has_permission("tenant:write"));
+
+        assert!(TenantContextSigner::new("other-secret").verify(&issued.token).is_err());
+    }
+
+    #[test]
+    fn test_expired_and_login_tokens_are_rejected() {
+        let signer = TenantContextSigner::new("test-secret-key").with_ttl(-10);
+        let issued = signer.issue(grant()).unwrap();
+        assert!(signer.verify(&issued.token).is_err());
+
+        // A login token signed with the same secret has no context audience
+        let login_token = crate::auth::AuthManager::new("test-secret-key")
+            .generate_token("user-1", "tenant-1", "user@example.com", vec![])
+            .unwrap();
+        assert!(TenantContextSigner::new("test-secret-key").verify(&login_token).is_err());
+    }
+}
@@ -1,6 +1,10 @@
 // Database utilities and abstractions
 
+pub mod encryption;
+pub mod isolation;
+
 use sqlx::{PgPool, Row};
+use crate::telemetry::traced_query;
 use crate::{Result, ServiceError};
 
 pub struct DatabaseManager {
@@ -18,16 +22,13 @@ impl DatabaseManager {
     }
     
     pub async fn health_check(&self) -> Result<()> {
-        sqlx::query("SELECT 1")
-            .fetch_one(&self.pool)
-            .await?;
+        traced_query("SELECT 1", sqlx::query("SELECT 1").fetch_one(&self.pool)).await?;
         Ok(())
     }
     
     pub async fn get_version(&self) -> Result<String> {
-        let row = sqlx::query("SELECT version()")
-            .fetch_one(&self.pool)
-            .await?;
+        let statement = "SELECT version()";
+        let row = traced_query(statement, sqlx::query(statement).fetch_one(&self.pool)).await?;
         
         Ok(row.get::<String, _>(0))
     }
@@ -0,0 +1,500 @@
+// Field-level encryption
+//
+// Sensitive columns (PII, tokens, secrets) are stored as ciphertext in TEXT
+// columns and decrypted when a record is read back. Every tenant gets its
+// own keys, derived from the platform's master keys, so one tenant's key
+// never opens another tenant's data.
+//
+// Encrypted columns cannot be searched, so a column that is looked up by
+// value keeps a blind index alongside it: an HMAC of the normalized value
+// under the tenant's index key. Equal values give equal indexes; the index
+// reveals nothing else.
+//
+// A model opts in with `encrypted_fields!`:
+//
+//     encrypted_fields! {
+//         User {
+//             tenant: tenant_id,
+//             encrypted: [email, phone],
+//             blind_indexes: [email => email_index as CaseInsensitive],
+//         }
+//     }
+//
+// and its repository calls `FieldCipher::encrypt_record` before writing and
+// `FieldCipher::decrypt_record` after reading.
+
+use aes_gcm::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use aes_gcm::{Aes256Gcm, Key, Nonce};
+use async_trait::async_trait;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use crate::{Result, ServiceError};
+
+/// Prefix marking a stored value as field ciphertext
+pub const ENCRYPTED_PREFIX: &str = "enc:v1:";
+
+const NONCE_LENGTH: usize = 12;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// How a value is normalized before it is indexed
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Normalization {
+    /// Indexed as stored; for tokens and identifiers
+    Exact,
+    /// Trimmed and lowercased; for emails and names
+    CaseInsensitive,
+}
+
+impl Normalization {
+    pub fn apply(&self, value: &str) -> String {
+        match self {
+            Normalization::Exact => value.to_string(),
+            Normalization::CaseInsensitive => value.trim().to_lowercase(),
+        }
+    }
+}
+
+/// A tenant's field keys. Values are encrypted with the active key and
+/// decrypted with whichever key they name, so older keys keep working after
+/// a rotation. The index key never rotates; indexes would stop matching.
+#[derive(Clone)]
+pub struct TenantFieldKeys {
+    pub active_kid: String,
+    pub keys: HashMap<String, [u8; 32]>,
+    pub index_key: [u8; 32],
+}
+
+impl std::fmt::Debug for TenantFieldKeys {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TenantFieldKeys")
+            .field("active_kid", &self.active_kid)
+            .field("kids", &self.keys.keys().collect::<Vec<_>>())
+            .finish_non_exhaustive()
+    }
+}
+
+/// Where a tenant's field keys come from
+#[async_trait]
+pub trait FieldKeyProvider: Send + Sync {
+    async fn tenant_keys(&self, tenant_id: &str) -> Result<TenantFieldKeys>;
+}
+
+/// Derives each tenant's keys from versioned master keys with HMAC-SHA256,
+/// so no per-tenant key has to be stored
+pub struct DerivedFieldKeyProvider {
+    /// Newest first; the first one encrypts
+    master_keys: Vec<(String, [u8; 32])>,
+    index_master_key: [u8; 32],
+}
+
+impl DerivedFieldKeyProvider {
+    pub fn new(master_keys: Vec<(String, [u8; 32])>, index_master_key: [u8; 32]) -> Result<Self> {
+        if master_keys.is_empty() {
+            return Err(ServiceError::Configuration("At least one field encryption key is required".to_string()));
+        }
+        if master_keys.iter().any(|(kid, _)| kid.is_empty() || kid.contains(':')) {
+            return Err(ServiceError::Configuration(
+                "Field encryption key ids must be non-empty and cannot contain ':'".to_string(),
+            ));
+        }
+
+        Ok(Self {
+            master_keys,
+            index_master_key,
+        })
+    }
+
+    /// Reads `FIELD_ENCRYPTION_KEYS` ("kid:base64key,..." newest first) and
+    /// `FIELD_BLIND_INDEX_KEY` (base64), each key 32 bytes
+    pub fn from_env() -> Result<Self> {
+        let keys = std::env::var("FIELD_ENCRYPTION_KEYS")
+            .map_err(|_| ServiceError::Configuration("FIELD_ENCRYPTION_KEYS is not set".to_string()))?;
+        let index_key = std::env::var("FIELD_BLIND_INDEX_KEY")
+            .map_err(|_| ServiceError::Configuration("FIELD_BLIND_INDEX_KEY is not set".to_string()))?;
+
+        let master_keys = keys
+            .split(',')
+            .map(str::trim)
+            .filter(|entry| !entry.is_empty())
+            .map(|entry| {
+                let (kid, key) = entry.split_once(':').ok_or_else(|| {
+                    ServiceError::Configuration("FIELD_ENCRYPTION_KEYS entries look like kid:base64key".to_string())
+                })?;
+                Ok((kid.to_string(), decode_key(key)?))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Self::new(master_keys, decode_key(&index_key)?)
+    }
+}
+
+#[async_trait]
+impl FieldKeyProvider for DerivedFieldKeyProvider {
+    async fn tenant_keys(&self, tenant_id: &str) -> Result<TenantFieldKeys> {
+        let keys = self
+            .master_keys
+            .iter()
+            .map(|(kid, master)| (kid.clone(), derive_key(master, "field-encryption", tenant_id)))
+            .collect();
+
+        Ok(TenantFieldKeys {
+            active_kid: self.master_keys[0].0.clone(),
+            keys,
+            index_key: derive_key(&self.index_master_key, "blind-index", tenant_id),
+        })
+    }
+}
+
+fn decode_key(encoded: &str) -> Result<[u8; 32]> {
+    BASE64
+        .decode(encoded.trim())
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| ServiceError::Configuration("Field encryption keys are 32 bytes, base64 encoded".to_string()))
+}
+
+fn derive_key(master: &[u8; 32], purpose: &str, tenant_id: &str) -> [u8; 32] {
+    let mut mac = <HmacSha256 as Mac>::new_from_slice(master).expect("HMAC takes keys of any length");
+    mac.update(purpose.as_bytes());
+    mac.update(b"\0");
+    mac.update(tenant_id.as_bytes());
+    mac.finalize().into_bytes().into()
+}
+
+/// A field holding text that can be encrypted in place
+pub trait EncryptableField: Send {
+    fn value(&self) -> Option<&str>;
+    fn set_value(&mut self, value: String);
+}
+
+impl EncryptableField for String {
+    fn value(&self) -> Option<&str> {
+        Some(self)
+    }
+
+    fn set_value(&mut self, value: String) {
+        *self = value;
+    }
+}
+
+impl EncryptableField for Option<String> {
+    fn value(&self) -> Option<&str> {
+        self.as_deref()
+    }
+
+    fn set_value(&mut self, value: String) {
+        *self = Some(value);
+    }
+}
+
+/// A blind index kept next to an encrypted field
+pub struct BlindIndexField<'a> {
+    /// The encrypted field the index is computed from
+    pub field: &'static str,
+    pub normalization: Normalization,
+    pub source: Option<&'a str>,
+    pub index: &'a mut dyn EncryptableField,
+}
+
+/// Implemented by `encrypted_fields!`
+pub trait EncryptedFields {
+    /// Tenant whose keys protect the record
+    fn field_tenant_id(&self) -> String;
+    fn encrypted_fields(&mut self) -> Vec<(&'static str, &mut dyn EncryptableField)>;
+    fn blind_index_fields(&mut self) -> Vec<BlindIndexField<'_>>;
+}
+
+/// Implement `EncryptedFields` for a model. Encrypted fields and blind
+/// indexes are `String` or `Option<String>`; the tenant field is anything
+/// that displays as the tenant id.
+#[macro_export]
+macro_rules! encrypted_fields {
+    (
+        $ty:ty {
+            tenant: $tenant:ident,
+            encrypted: [$($field:ident),* $(,)?]
+            $(, blind_indexes: [$($source:ident => $index:ident as $normalization:ident),* $(,)?])?
+            $(,)?
+        }
+    ) => {
+        impl $crate::database::encryption::EncryptedFields for $ty {
+            fn field_tenant_id(&self) -> String {
+                self.$tenant.to_string()
+            }
+
+            fn encrypted_fields(
+                &mut self,
+            ) -> Vec<(&'static str, &mut dyn $crate::database::encryption::EncryptableField)> {
+                vec![$((
+                    stringify!($field),
+                    &mut self.$field as &mut dyn $crate::database::encryption::EncryptableField,
+                )),*]
+            }
+
+            fn blind_index_fields(&mut self) -> Vec<$crate::database::encryption::BlindIndexField<'_>> {
+                #[allow(unused_mut)]
+                let mut fields = Vec::new();
+                $($(
+                    fields.push($crate::database::encryption::BlindIndexField {
+                        field: stringify!($source),
+                        normalization: $crate::database::encryption::Normalization::$normalization,
+                        source: $crate::database::encryption::EncryptableField::value(&self.$source),
+                        index: &mut self.$index,
+                    });
+                )*)?
+                fields
+            }
+        }
+    };
+}
+
+/// Encrypts and decrypts fields with per-tenant keys
+#[derive(Clone)]
+pub struct FieldCipher {
+    provider: Arc<dyn FieldKeyProvider>,
+}
+
+impl FieldCipher {
+    pub fn new(provider: Arc<dyn FieldKeyProvider>) -> Self {
+        Self { provider }
+    }
+
+    pub fn from_env() -> Result<Self> {
+        Ok(Self::new(Arc::new(DerivedFieldKeyProvider::from_env()?)))
+    }
+
+    pub fn is_encrypted(value: &str) -> bool {
+        value.starts_with(ENCRYPTED_PREFIX)
+    }
+
+    /// Encrypt one value of a tenant's field. The ciphertext is bound to the
+    /// tenant and field, so it cannot be copied into another row or column.
+    pub async fn encrypt(&self, tenant_id: &str, field: &str, plaintext: &str) -> Result<String> {
+        let keys = self.provider.tenant_keys(tenant_id).await?;
+        encrypt_value(&keys, tenant_id, field, plaintext)
+    }
+
+    /// Decrypt one value. Values stored before the field was encrypted are
+    /// returned as they are.
+    pub async fn decrypt(&self, tenant_id: &str, field: &str, stored: &str) -> Result<String> {
+        if !Self::is_encrypted(stored) {
+            return Ok(stored.to_string());
+        }
+        let keys = self.provider.tenant_keys(tenant_id).await?;
+        decrypt_value(&keys, tenant_id, field, stored)
+    }
+
+    /// The blind index to look a value up by, e.g. `WHERE email_index = $1`
+    pub async fn blind_index(
+        &self,
+        tenant_id: &str,
+        field: &str,
+        value: &str,
+        normalization: Normalization,
+    ) -> Result<String> {
+        let keys = self.provider.tenant_keys(tenant_id).await?;
+        Ok(blind_index_value(&keys, field, value, normalization))
+    }
+
+    /// Fill in the record's blind indexes and encrypt its fields. Fields
+    /// that are already encrypted are left alone.
+    pub async fn encrypt_record<T: EncryptedFields>(&self, record: &mut T) -> Result<()> {
+        let tenant_id = record.field_tenant_id();
+        let keys = self.provider.tenant_keys(&tenant_id).await?;
+
+        for BlindIndexField { field, normalization, source, index } in record.blind_index_fields() {
+            if let Some(source) = source.filter(|source| !Self::is_encrypted(source)) {
+                index.set_value(blind_index_value(&keys, field, source, normalization));
+            }
+        }
+
+        for (field, value) in record.encrypted_fields() {
+            let ciphertext = match value.value() {
+                Some(plaintext) if !Self::is_encrypted(plaintext) => {
+                    encrypt_value(&keys, &tenant_id, field, plaintext)?
+                }
+                _ => continue,
+            };
+            value.set_value(ciphertext);
+        }
+
+        Ok(())
+    }
+
+    pub async fn decrypt_record<T: EncryptedFields>(&self, record: &mut T) -> Result<()> {
+        let tenant_id = record.field_tenant_id();
+        let keys = self.provider.tenant_keys(&tenant_id).await?;
+
+        for (field, value) in record.encrypted_fields() {
+            let plaintext = match value.value() {
+                Some(stored) if Self::is_encrypted(stored) => decrypt_value(&keys, &tenant_id, field, stored)?,
+                _ => continue,
+            };
+            value.set_value(plaintext);
+        }
+
+        Ok(())
+    }
+}
+
+fn associated_data(tenant_id: &str, field: &str) -> Vec<u8> {
+    format!("{}\0{}", tenant_id, field).into_bytes()
+}
+
+fn encrypt_value(keys: &TenantFieldKeys, tenant_id: &str, field: &str, plaintext: &str) -> Result<String> {
+    let key = keys
+        .keys
+        .get(&keys.active_kid)
+        .ok_or_else(|| ServiceError::Configuration(format!("Field key {} is not available", keys.active_kid)))?;
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let nonce = Aes256Gcm::generate_nonce(&mut OsRng);
+
+    let ciphertext = cipher
+        .encrypt(&nonce, Payload { msg: plaintext.as_bytes(), aad: &associated_data(tenant_id, field) })
+        .map_err(|_| ServiceError::Internal(format!("Failed to encrypt field {}", field)))?;
+
+    let mut sealed = nonce.to_vec();
+    sealed.extend_from_slice(&ciphertext);
+    Ok(format!("{}{}:{}", ENCRYPTED_PREFIX, keys.active_kid, BASE64.encode(sealed)))
+}
+
+fn decrypt_value(keys: &TenantFieldKeys, tenant_id: &str, field: &str, stored: &str) -> Result<String> {
+    let malformed = || ServiceError::Internal(format!("Field {} holds malformed ciphertext", field));
+
+    let (kid, sealed) = stored
+        .strip_prefix(ENCRYPTED_PREFIX)
+        .and_then(|rest| rest.split_once(':'))
+        .ok_or_else(malformed)?;
+    let key = keys
+        .keys
+        .get(kid)
+        .ok_or_else(|| ServiceError::Configuration(format!("Field key {} is not available", kid)))?;
+    let sealed = BASE64.decode(sealed).map_err(|_| malformed())?;
+    if sealed.len() <= NONCE_LENGTH {
+        return Err(malformed());
+    }
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LENGTH);
+
+    let cipher = Aes256Gcm::new(Key::<Aes256Gcm>::from_slice(key));
+    let plaintext = cipher
+        .decrypt(Nonce::from_slice(nonce), Payload { msg: ciphertext, aad: &associated_data(tenant_id, field) })
+        .map_err(|_| ServiceError::Internal(format!("Failed to decrypt field {}", field)))?;
+
+    String::from_utf8(plaintext).map_err(|_| malformed())
+}
+
+fn blind_index_value(keys: &TenantFieldKeys, field: &str, value: &str, normalization: Normalization) -> String {
+    let mut mac = <HmacSha256 as Mac>::new_from_slice(&keys.index_key).expect("HMAC takes keys of any length");
+    mac.update(field.as_bytes());
+    mac.update(b"\0");
+    mac.update(normalization.apply(value).as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Contact {
+        tenant_id: String,
+        email: String,
+        email_index: Option<String>,
+        phone: Option<String>,
+        nickname: String,
+    }
+
+    encrypted_fields! {
+        Contact {
+            tenant: tenant_id,
+            encrypted: [email, phone],
+            blind_indexes: [email => email_index as CaseInsensitive],
+        }
+    }
+
+    fn contact(tenant_id: &str) -> Contact {
+        Contact {
+            tenant_id: tenant_id.to_string(),
+            email: "Ada@Example.com".to_string(),
+            email_index: None,
+            phone: Some("+44 20 7946 0000".to_string()),
+            nickname: "ada".to_string(),
+        }
+    }
+
+    fn cipher(master_keys: &[(&str, u8)]) -> FieldCipher {
+        let keys = master_keys.iter().map(|(kid, byte)| (kid.to_string(), [*byte; 32])).collect();
+        FieldCipher::new(Arc::new(DerivedFieldKeyProvider::new(keys, [9; 32]).unwrap()))
+    }
+
+    #[tokio::test]
+    async fn test_record_round_trip() {
+        let cipher = cipher(&[("k1", 1)]);
+        let mut record = contact("tenant-a");
+
+        cipher.encrypt_record(&mut record).await.unwrap();
+        assert!(FieldCipher::is_encrypted(&record.email));
+        assert!(FieldCipher::is_encrypted(record.phone.as_deref().unwrap()));
+        assert_eq!(record.nickname, "ada");
+        let index = cipher
+            .blind_index("tenant-a", "email", " ada@example.COM", Normalization::CaseInsensitive)
+            .await
+            .unwrap();
+        assert_eq!(record.email_index, Some(index));
+
+        // Encrypting twice leaves the ciphertext and index alone
+        let email = record.email.clone();
+        cipher.encrypt_record(&mut record).await.unwrap();
+        assert_eq!(record.email, email);
+
+        cipher.decrypt_record(&mut record).await.unwrap();
+        assert_eq!(record.email, "Ada@Example.com");
+        assert_eq!(record.phone.as_deref(), Some("+44 20 7946 0000"));
+    }
+
+    #[tokio::test]
+    async fn test_ciphertext_is_bound_to_tenant_and_field() {
+        let cipher = cipher(&[("k1", 1)]);
+        let ciphertext = cipher.encrypt("tenant-a", "email", "ada@example.com").await.unwrap();
+
+        assert!(cipher.decrypt("tenant-b", "email", &ciphertext).await.is_err());
+        assert!(cipher.decrypt("tenant-a", "phone", &ciphertext).await.is_err());
+        assert_eq!(cipher.decrypt("tenant-a", "email", &ciphertext).await.unwrap(), "ada@example.com");
+    }
+
+    #[tokio::test]
+    async fn test_blind_indexes_differ_per_tenant() {
+        let cipher = cipher(&[("k1", 1)]);
+        let a = cipher.blind_index("tenant-a", "email", "ada@example.com", Normalization::Exact).await.unwrap();
+        let b = cipher.blind_index("tenant-b", "email", "ada@example.com", Normalization::Exact).await.unwrap();
+
+        assert_ne!(a, b);
+        assert_ne!(a, cipher.blind_index("tenant-a", "email", "ADA@example.com", Normalization::Exact).await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn test_rotated_keys_still_decrypt() {
+        let old = cipher(&[("k1", 1)]);
+        let ciphertext = old.encrypt("tenant-a", "email", "ada@example.com").await.unwrap();
+
+        let rotated = cipher(&[("k2", 2), ("k1", 1)]);
+        assert_eq!(rotated.decrypt("tenant-a", "email", &ciphertext).await.unwrap(), "ada@example.com");
+        assert!(rotated.encrypt("tenant-a", "email", "x").await.unwrap().starts_with("enc:v1:k2:"));
+
+        // Indexes survive the rotation
+        assert_eq!(
+            old.blind_index("tenant-a", "email", "ada@example.com", Normalization::Exact).await.unwrap(),
+            rotated.blind_index("tenant-a", "email", "ada@example.com", Normalization::Exact).await.unwrap()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_plaintext_values_pass_through_decrypt() {
+        let cipher = cipher(&[("k1", 1)]);
+        assert_eq!(cipher.decrypt("tenant-a", "email", "legacy@example.com").await.unwrap(), "legacy@example.com");
+    }
+}
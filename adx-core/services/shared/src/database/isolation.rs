@@ -0,0 +1,339 @@
+// Tenant isolation strategies
+//
+// Every tenant is placed with one of three strategies, chosen when the tenant
+// is created:
+// - Row: shared schema, rows filtered by `tenant_id` through RLS policies
+// - Schema: a dedicated `tenant_<id>` schema selected through `search_path`
+// - Database: a dedicated database with its own connection pool
+//
+// `TenantPoolRouter` hands out connections with the right scope already
+// applied so repositories do not need to know which strategy a tenant uses.
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+
+use sqlx::pool::PoolConnection;
+use sqlx::postgres::{PgPoolOptions, Postgres};
+use sqlx::{Executor, PgConnection, PgPool, Transaction};
+use tokio::sync::RwLock;
+
+use crate::types::{TenantId, TenantIsolationLevel};
+use crate::{Result, ServiceError};
+
+/// Postgres truncates identifiers beyond this length
+const MAX_IDENTIFIER_LENGTH: usize = 63;
+
+/// Session setting read by the row-level security policies
+pub const TENANT_ID_SETTING: &str = "app.current_tenant_id";
+
+/// Where a tenant's data lives
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct TenantPlacement {
+    pub tenant_id: TenantId,
+    pub isolation_level: TenantIsolationLevel,
+    /// Dedicated schema for schema-per-tenant placement
+    pub schema_name: Option<String>,
+    /// Dedicated database for database-per-tenant placement
+    pub database_name: Option<String>,
+}
+
+impl TenantPlacement {
+    pub fn new(tenant_id: &str, isolation_level: TenantIsolationLevel) -> Result<Self> {
+        let (schema_name, database_name) = match isolation_level {
+            TenantIsolationLevel::Row => (None, None),
+            TenantIsolationLevel::Schema => (Some(tenant_identifier(tenant_id)?), None),
+            TenantIsolationLevel::Database => (None, Some(tenant_identifier(tenant_id)?)),
+        };
+
+        Ok(Self {
+            tenant_id: tenant_id.to_string(),
+            isolation_level,
+            schema_name,
+            database_name,
+        })
+    }
+
+    /// `search_path` for connections scoped to this tenant
+    pub fn search_path(&self) -> String {
+        match &self.schema_name {
+            Some(schema) => format!("{}, public", quote_identifier(schema)),
+            None => "public".to_string(),
+        }
+    }
+
+    /// Extra predicate for queries against shared tables. Only row-level
+    /// tenants share tables, so other placements need no filter.
+    pub fn row_filter(&self, column: &str, param_index: usize) -> Option<String> {
+        match self.isolation_level {
+            TenantIsolationLevel::Row => Some(format!("{} = ${}", column, param_index)),
+            _ => None,
+        }
+    }
+
+    /// Statements that scope a connection to this tenant. `local` limits them
+    /// to the current transaction.
+    pub fn scope_statements(&self, local: bool) -> Vec<String> {
+        let set = if local { "SET LOCAL" } else { "SET" };
+
+        vec![
+            // The tenant id is always set so RLS policies hold under every strategy
+            format!("{} {} = {}", set, TENANT_ID_SETTING, quote_literal(&self.tenant_id)),
+            format!("{} search_path TO {}", set, self.search_path()),
+        ]
+    }
+}
+
+/// Schema/database name for a tenant: `tenant_` plus the id with separators
+/// replaced, restricted to characters that never need escaping
+pub fn tenant_identifier(tenant_id: &str) -> Result<String> {
+    let normalized: String = tenant_id
+        .to_lowercase()
+        .chars()
+        .map(|c| if c == '-' { '_' } else { c })
+        .collect();
+
+    if normalized.is_empty() || !normalized.chars().all(|c| c.is_ascii_alphanumeric() || c == '_') {
+        return Err(ServiceError::Validation(format!("Invalid tenant id for isolation: {}", tenant_id)));
+    }
+
+    let identifier = format!("tenant_{}", normalized);
+    if identifier.len() > MAX_IDENTIFIER_LENGTH {
+        return Err(ServiceError::Validation(format!(
+            "Tenant identifier exceeds {} characters: {}",
+            MAX_IDENTIFIER_LENGTH, identifier
+        )));
+    }
+
+    Ok(identifier)
+}
+
+fn quote_identifier(identifier: &str) -> String {
+    format!("\"{}\"", identifier.replace('"', "\"\""))
+}
+
+fn quote_literal(value: &str) -> String {
+    format!("'{}'", value.replace('\'', "''"))
+}
+
+/// Replace the database name in a Postgres URL, keeping any query string
+pub fn database_url_for(base_url: &str, database_name: &str) -> Result<String> {
+    let (without_query, query) = match base_url.split_once('?') {
+        Some((url, query)) => (url, Some(query)),
+        None => (base_url, None),
+    };
+
+    let authority_start = without_query
+        .find("://")
+        .map(|index| index + 3)
+        .ok_or_else(|| ServiceError::Configuration(format!("Invalid database URL: {}", base_url)))?;
+
+    let host_part = match without_query[authority_start..].find('/') {
+        Some(index) => &without_query[..authority_start + index],
+        None => without_query,
+    };
+
+    Ok(match query {
+        Some(query) => format!("{}/{}?{}", host_part, database_name, query),
+        None => format!("{}/{}", host_part, database_name),
+    })
+}
+
+/// Routes tenant connections to the shared pool or a dedicated one and
+/// applies the tenant's scope before handing them out
+pub struct TenantPoolRouter {
+    shared_pool: PgPool,
+    base_url: String,
+    max_connections_per_tenant: u32,
+    placements: RwLock<HashMap<TenantId, TenantPlacement>>,
+    dedicated_pools: RwLock<HashMap<TenantId, PgPool>>,
+}
+
+impl TenantPoolRouter {
+    pub fn new(shared_pool: PgPool, base_url: &str) -> Self {
+        Self {
+            shared_pool,
+            base_url: base_url.to_string(),
+            max_connections_per_tenant: 5,
+            placements: RwLock::new(HashMap::new()),
+            dedicated_pools: RwLock::new(HashMap::new()),
+        }
+    }
+
+    pub fn with_max_connections_per_tenant(mut self, max_connections: u32) -> Self {
+        self.max_connections_per_tenant = max_connections;
+        self
+    }
+
+    pub async fn register(&self, placement: TenantPlacement) {
+        self.placements.write().await.insert(placement.tenant_id.clone(), placement);
+    }
+
+    pub async fn unregister(&self, tenant_id: &str) {
+        self.placements.write().await.remove(tenant_id);
+        if let Some(pool) = self.dedicated_pools.write().await.remove(tenant_id) {
+            pool.close().await;
+        }
+    }
+
+    pub async fn placement(&self, tenant_id: &str) -> Result<TenantPlacement> {
+        self.placements
+            .read()
+            .await
+            .get(tenant_id)
+            .cloned()
+            .ok_or_else(|| ServiceError::Tenant(format!("No isolation placement registered for tenant {}", tenant_id)))
+    }
+
+    async fn pool_for(&self, placement: &TenantPlacement) -> Result<PgPool> {
+        let Some(database_name) = &placement.database_name else {
+            return Ok(self.shared_pool.clone());
+        };
+
+        if let Some(pool) = self.dedicated_pools.read().await.get(&placement.tenant_id) {
+            return Ok(pool.clone());
+        }
+
+        let mut pools = self.dedicated_pools.write().await;
+        // Another task may have created the pool while we waited for the lock
+        if let Some(pool) = pools.get(&placement.tenant_id) {
+            return Ok(pool.clone());
+        }
+
+        let pool = PgPoolOptions::new()
+            .max_connections(self.max_connections_per_tenant)
+            .connect(&database_url_for(&self.base_url, database_name)?)
+            .await?;
+        pools.insert(placement.tenant_id.clone(), pool.clone());
+
+        Ok(pool)
+    }
+
+    /// Connection scoped to the tenant for its whole checkout. The scope is
+    /// reapplied on every acquire, so a pooled connection never keeps a
+    /// previous tenant's settings.
+    pub async fn acquire(&self, tenant_id: &str) -> Result<PoolConnection<Postgres>> {
+        let placement = self.placement(tenant_id).await?;
+        let mut connection = self.pool_for(&placement).await?.acquire().await?;
+
+        apply_scope(&mut connection, &placement, false).await?;
+        Ok(connection)
+    }
+
+    /// Transaction scoped to the tenant with `SET LOCAL`; preferred for writes
+    pub async fn begin(&self, tenant_id: &str) -> Result<Transaction<'static, Postgres>> {
+        let placement = self.placement(tenant_id).await?;
+        let mut transaction = self.pool_for(&placement).await?.begin().await?;
+
+        apply_scope(&mut transaction, &placement, true).await?;
+        Ok(transaction)
+    }
+
+    /// Create the schema or database a tenant's placement needs
+    pub async fn provision(&self, placement: &TenantPlacement) -> Result<()> {
+        if let Some(schema) = &placement.schema_name {
+            self.shared_pool
+                .execute(format!("CREATE SCHEMA IF NOT EXISTS {}", quote_identifier(schema)).as_str())
+                .await?;
+        }
+
+        if let Some(database) = &placement.database_name {
+            let exists: bool = sqlx::query_scalar("SELECT EXISTS (SELECT 1 FROM pg_database WHERE datname = $1)")
+                .bind(database)
+                .fetch_one(&self.shared_pool)
+                .await?;
+
+            if !exists {
+                // CREATE DATABASE cannot run inside a transaction block
+                self.shared_pool
+                    .execute(format!("CREATE DATABASE {}", quote_identifier(database)).as_str())
+                    .await?;
+            }
+        }
+
+        self.register(placement.clone()).await;
+        Ok(())
+    }
+
+    /// Drop the schema or database created by `provision`
+    pub async fn deprovision(&self, placement: &TenantPlacement) -> Result<()> {
+        self.unregister(&placement.tenant_id).await;
+
+        if let Some(schema) = &placement.schema_name {
+            self.shared_pool
+                .execute(format!("DROP SCHEMA IF EXISTS {} CASCADE", quote_identifier(schema)).as_str())
+                .await?;
+        }
+
+        if let Some(database) = &placement.database_name {
+            self.shared_pool
+                .execute(format!("DROP DATABASE IF EXISTS {}", quote_identifier(database)).as_str())
+                .await?;
+        }
+
+        Ok(())
+    }
+}
+
+async fn apply_scope(connection: &mut PgConnection, placement: &TenantPlacement, local: bool) -> Result<()> {
+    for statement in placement.scope_statements(local) {
+        connection.execute(statement.as_str()).await?;
+    }
+    Ok(())
+}
+
+/// Shared router handle for services
+pub type SharedTenantPoolRouter = Arc<TenantPoolRouter>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_placement_per_isolation_level() {
+        let tenant_id = "6F9619FF-8B86-D011-B42D-00C04FC964FF";
+
+        let row = TenantPlacement::new(tenant_id, TenantIsolationLevel::Row).unwrap();
+        assert_eq!(row.schema_name, None);
+        assert_eq!(row.search_path(), "public");
+        assert_eq!(row.row_filter("tenant_id", 1).as_deref(), Some("tenant_id = $1"));
+
+        let schema = TenantPlacement::new(tenant_id, TenantIsolationLevel::Schema).unwrap();
+        assert_eq!(schema.schema_name.as_deref(), Some("tenant_6f9619ff_8b86_d011_b42d_00c04fc964ff"));
+        assert_eq!(schema.search_path(), "\"tenant_6f9619ff_8b86_d011_b42d_00c04fc964ff\", public");
+        assert_eq!(schema.row_filter("tenant_id", 1), None);
+
+        let database = TenantPlacement::new(tenant_id, TenantIsolationLevel::Database).unwrap();
+        assert!(database.database_name.is_some());
+        assert_eq!(database.search_path(), "public");
+    }
+
+    #[test]
+    fn test_scope_statements_always_set_tenant() {
+        let placement = TenantPlacement::new("acme", TenantIsolationLevel::Schema).unwrap();
+        let statements = placement.scope_statements(true);
+
+        assert_eq!(statements[0], "SET LOCAL app.current_tenant_id = 'acme'");
+        assert_eq!(statements[1], "SET LOCAL search_path TO \"tenant_acme\", public");
+    }
+
+    #[test]
+    fn test_tenant_identifier_rejects_unsafe_ids() {
+        assert!(tenant_identifier("acme; DROP SCHEMA public").is_err());
+        assert!(tenant_identifier("").is_err());
+        assert!(tenant_identifier(&"a".repeat(60)).is_err());
+    }
+
+    #[test]
+    fn test_database_url_for() {
+        assert_eq!(
+            database_url_for("postgres://u:p@db:5432/adx_core?sslmode=require", "tenant_acme").unwrap(),
+            "postgres://u:p@db:5432/tenant_acme?sslmode=require"
+        );
+        assert_eq!(
+            database_url_for("postgres://db:5432", "tenant_acme").unwrap(),
+            "postgres://db:5432/tenant_acme"
+        );
+        assert!(database_url_for("not a url", "tenant_acme").is_err());
+    }
+}
@@ -0,0 +1,586 @@
+// Data loss prevention
+//
+// Tenants configure DLP rules in security-service: what to look for (card
+// numbers, SSNs, their own patterns and word lists), where (file uploads,
+// AI prompts) and what to do about it (block, redact, alert). Services scan
+// content through `DlpClient` before accepting it and report every hit as a
+// `dlp_incident` audit event. Incidents name the rules that matched and how
+// often, never the matched text.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use regex::{Regex, RegexBuilder};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::ops::Range;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+use crate::audit::{AuditCategory, AuditEvent, AuditOutcome};
+use crate::pii::{credit_card_pattern, luhn_valid, ssn_pattern, ssn_valid};
+use crate::{Result, ServiceError};
+
+pub const DEFAULT_DLP_POLICY_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Audit action incidents are recorded under
+pub const DLP_INCIDENT_ACTION: &str = "dlp_incident";
+
+const REDACTION: &str = "[REDACTED]";
+
+/// Where content is scanned
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum DlpChannel {
+    FileUpload,
+    AiPrompt,
+}
+
+impl DlpChannel {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DlpChannel::FileUpload => "file_upload",
+            DlpChannel::AiPrompt => "ai_prompt",
+        }
+    }
+}
+
+/// What a rule does on a match, weakest first
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "snake_case")]
+pub enum DlpAction {
+    /// Let the content through and record an incident
+    Alert,
+    /// Replace the matches and let the rest through
+    Redact,
+    /// Reject the content
+    Block,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum DlpDetector {
+    /// Card numbers passing the Luhn check
+    CreditCard,
+    /// US social security numbers in the 123-45-6789 form
+    Ssn,
+    Regex { pattern: String },
+    /// Whole-word, case-insensitive matches of any of the terms
+    Dictionary { terms: Vec<String> },
+}
+
+impl DlpDetector {
+    pub fn name(&self) -> &'static str {
+        match self {
+            DlpDetector::CreditCard => "credit_card",
+            DlpDetector::Ssn => "ssn",
+            DlpDetector::Regex { .. } => "regex",
+            DlpDetector::Dictionary { .. } => "dictionary",
+        }
+    }
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DlpRule {
+    pub id: String,
+    pub name: String,
+    pub detector: DlpDetector,
+    pub action: DlpAction,
+    /// Channels the rule applies to; empty means all of them
+    #[serde(default)]
+    pub channels: Vec<DlpChannel>,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+}
+
+impl DlpRule {
+    fn applies_to(&self, channel: DlpChannel) -> bool {
+        self.enabled && (self.channels.is_empty() || self.channels.contains(&channel))
+    }
+}
+
+/// A tenant's DLP rules
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DlpPolicy {
+    pub tenant_id: String,
+    pub enabled: bool,
+    pub rules: Vec<DlpRule>,
+    pub updated_by: Option<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl DlpPolicy {
+    /// The policy of a tenant that has not configured one: nothing is scanned
+    pub fn disabled(tenant_id: &str) -> Self {
+        Self {
+            tenant_id: tenant_id.to_string(),
+            enabled: false,
+            rules: Vec::new(),
+            updated_by: None,
+            updated_at: None,
+        }
+    }
+
+    /// Compile the rules. Fails on an invalid pattern, an empty dictionary
+    /// or duplicate rule ids.
+    pub fn compile(&self) -> Result<DlpScanner> {
+        let mut rules = Vec::with_capacity(self.rules.len());
+        for rule in &self.rules {
+            if rules.iter().any(|compiled: &CompiledRule| compiled.rule.id == rule.id) {
+                return Err(ServiceError::Validation(format!("Duplicate DLP rule id {}", rule.id)));
+            }
+
+            let pattern = match &rule.detector {
+                DlpDetector::CreditCard => credit_card_pattern().clone(),
+                DlpDetector::Ssn => ssn_pattern().clone(),
+                DlpDetector::Regex { pattern } => Regex::new(pattern).map_err(|e| {
+                    ServiceError::Validation(format!("Invalid pattern in DLP rule {}: {}", rule.id, e))
+                })?,
+                DlpDetector::Dictionary { terms } => {
+                    let alternatives: Vec<String> = terms
+                        .iter()
+                        .map(|term| term.trim())
+                        .filter(|term| !term.is_empty())
+                        .map(regex::escape)
+                        .collect();
+                    if alternatives.is_empty() {
+                        return Err(ServiceError::Validation(format!("DLP rule {} has no dictionary terms", rule.id)));
+                    }
+                    RegexBuilder::new(&format!(r"\b(?:{})\b", alternatives.join("|")))
+                        .case_insensitive(true)
+                        .build()
+                        .map_err(|e| ServiceError::Validation(format!("Invalid dictionary in DLP rule {}: {}", rule.id, e)))?
+                }
+            };
+
+            rules.push(CompiledRule {
+                rule: rule.clone(),
+                pattern,
+            });
+        }
+
+        Ok(DlpScanner {
+            tenant_id: self.tenant_id.clone(),
+            enabled: self.enabled,
+            rules,
+        })
+    }
+}
+
+struct CompiledRule {
+    rule: DlpRule,
+    pattern: Regex,
+}
+
+impl CompiledRule {
+    fn matches(&self, text: &str) -> Vec<Range<usize>> {
+        self.pattern
+            .find_iter(text)
+            .filter(|found| match self.rule.detector {
+                DlpDetector::CreditCard => luhn_valid(found.as_str()),
+                DlpDetector::Ssn => ssn_valid(found.as_str()),
+                _ => true,
+            })
+            .map(|found| found.range())
+            .collect()
+    }
+}
+
+/// A rule that matched, and how often
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct DlpFinding {
+    pub rule_id: String,
+    pub rule_name: String,
+    pub detector: String,
+    pub action: DlpAction,
+    pub match_count: usize,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DlpScanResult {
+    pub channel: DlpChannel,
+    pub findings: Vec<DlpFinding>,
+    /// The strongest action among the findings
+    pub action: Option<DlpAction>,
+    /// The content with the matches of redacting rules replaced
+    pub text: String,
+}
+
+impl DlpScanResult {
+    pub fn is_blocked(&self) -> bool {
+        self.action == Some(DlpAction::Block)
+    }
+
+    pub fn is_redacted(&self) -> bool {
+        self.findings.iter().any(|finding| finding.action == DlpAction::Redact)
+    }
+
+    /// Names of the rules that blocked the content
+    pub fn blocking_rules(&self) -> Vec<&str> {
+        self.findings
+            .iter()
+            .filter(|finding| finding.action == DlpAction::Block)
+            .map(|finding| finding.rule_name.as_str())
+            .collect()
+    }
+
+    /// The incident to record, if anything matched
+    pub fn incident(
+        &self,
+        tenant_id: &str,
+        service: &str,
+        resource_type: &str,
+        resource_id: Option<&str>,
+        actor_id: Option<&str>,
+    ) -> Option<AuditEvent> {
+        let action = self.action?;
+
+        let mut event = AuditEvent::new(tenant_id, service, AuditCategory::Security, DLP_INCIDENT_ACTION, resource_type)
+            .outcome(if action == DlpAction::Block {
+                AuditOutcome::Failure
+            } else {
+                AuditOutcome::Warning
+            })
+            .details(json!({
+                "channel": self.channel,
+                "action": action,
+                "findings": self.findings,
+            }));
+        if let Some(resource_id) = resource_id {
+            event = event.resource_id(resource_id);
+        }
+        if let Some(actor_id) = actor_id {
+            event = event.actor(actor_id);
+        }
+        Some(event)
+    }
+}
+
+/// A tenant's compiled DLP rules
+pub struct DlpScanner {
+    tenant_id: String,
+    enabled: bool,
+    rules: Vec<CompiledRule>,
+}
+
+impl DlpScanner {
+    pub fn tenant_id(&self) -> &str {
+        &self.tenant_id
+    }
+
+    pub fn scan(&self, channel: DlpChannel, text: &str) -> DlpScanResult {
+        let mut findings = Vec::new();
+        let mut redactions = Vec::new();
+
+        if self.enabled {
+            for compiled in self.rules.iter().filter(|compiled| compiled.rule.applies_to(channel)) {
+                let matches = compiled.matches(text);
+                if matches.is_empty() {
+                    continue;
+                }
+
+                findings.push(DlpFinding {
+                    rule_id: compiled.rule.id.clone(),
+                    rule_name: compiled.rule.name.clone(),
+                    detector: compiled.rule.detector.name().to_string(),
+                    action: compiled.rule.action,
+                    match_count: matches.len(),
+                });
+                if compiled.rule.action == DlpAction::Redact {
+                    redactions.extend(matches);
+                }
+            }
+        }
+
+        DlpScanResult {
+            channel,
+            action: findings.iter().map(|finding| finding.action).max(),
+            findings,
+            text: redact(text, redactions),
+        }
+    }
+}
+
+/// Replace the ranges, merging overlapping ones
+fn redact(text: &str, mut ranges: Vec<Range<usize>>) -> String {
+    if ranges.is_empty() {
+        return text.to_string();
+    }
+    ranges.sort_by_key(|range| range.start);
+
+    let mut redacted = String::with_capacity(text.len());
+    let mut position = 0;
+    for range in ranges {
+        if range.start < position {
+            position = position.max(range.end);
+            continue;
+        }
+        redacted.push_str(&text[position..range.start]);
+        redacted.push_str(REDACTION);
+        position = range.end;
+    }
+    redacted.push_str(&text[position..]);
+    redacted
+}
+
+/// Where DLP policies come from
+#[async_trait]
+pub trait DlpPolicySource: Send + Sync {
+    async fn fetch_policy(&self, tenant_id: &str) -> Result<DlpPolicy>;
+}
+
+/// Reads DLP policies from security-service over HTTP
+pub struct HttpDlpPolicySource {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl HttpDlpPolicySource {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl DlpPolicySource for HttpDlpPolicySource {
+    async fn fetch_policy(&self, tenant_id: &str) -> Result<DlpPolicy> {
+        let url = format!("{}/api/v1/dlp/tenants/{}/policy", self.base_url, tenant_id);
+
+        let response = self
+            .client
+            .get(&url)
+            .header("X-Tenant-ID", tenant_id)
+            .send()
+            .await
+            .map_err(|e| ServiceError::ExternalService(format!("DLP policy request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(ServiceError::ExternalService(format!(
+                "DLP policy request for tenant {} returned {}",
+                tenant_id,
+                response.status()
+            )));
+        }
+
+        response
+            .json::<DlpPolicy>()
+            .await
+            .map_err(|e| ServiceError::ExternalService(format!("Invalid DLP policy response: {}", e)))
+    }
+}
+
+struct CachedScanner {
+    scanner: Arc<DlpScanner>,
+    fetched_at: Instant,
+}
+
+/// Cached, compiled DLP policies
+pub struct DlpClient {
+    source: Arc<dyn DlpPolicySource>,
+    ttl: Duration,
+    cache: RwLock<HashMap<String, CachedScanner>>,
+}
+
+impl DlpClient {
+    pub fn new(source: Arc<dyn DlpPolicySource>) -> Self {
+        Self {
+            source,
+            ttl: DEFAULT_DLP_POLICY_CACHE_TTL,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Client backed by security-service at `base_url`
+    pub fn from_url(base_url: &str) -> Self {
+        Self::new(Arc::new(HttpDlpPolicySource::new(base_url)))
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub async fn scanner(&self, tenant_id: &str) -> Result<Arc<DlpScanner>> {
+        if let Some(cached) = self.cache.read().await.get(tenant_id) {
+            if cached.fetched_at.elapsed() < self.ttl {
+                return Ok(cached.scanner.clone());
+            }
+        }
+
+        let fetched = self.source.fetch_policy(tenant_id).await.and_then(|policy| policy.compile());
+        match fetched {
+            Ok(scanner) => {
+                let scanner = Arc::new(scanner);
+                self.cache.write().await.insert(
+                    tenant_id.to_string(),
+                    CachedScanner {
+                        scanner: scanner.clone(),
+                        fetched_at: Instant::now(),
+                    },
+                );
+                Ok(scanner)
+            }
+            Err(e) => {
+                // Keep enforcing the last known rules rather than none at all
+                if let Some(cached) = self.cache.read().await.get(tenant_id) {
+                    tracing::warn!(tenant_id = tenant_id, error = %e, "Serving stale DLP policy");
+                    return Ok(cached.scanner.clone());
+                }
+                Err(e)
+            }
+        }
+    }
+
+    pub async fn scan(&self, tenant_id: &str, channel: DlpChannel, text: &str) -> Result<DlpScanResult> {
+        Ok(self.scanner(tenant_id).await?.scan(channel, text))
+    }
+
+    pub async fn invalidate(&self, tenant_id: &str) {
+        self.cache.write().await.remove(tenant_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(id: &str, detector: DlpDetector, action: DlpAction, channels: Vec<DlpChannel>) -> DlpRule {
+        DlpRule {
+            id: id.to_string(),
+            name: id.to_string(),
+            detector,
+            action,
+            channels,
+            enabled: true,
+        }
+    }
+
+    fn policy(rules: Vec<DlpRule>) -> DlpPolicy {
+        DlpPolicy {
+            rules,
+            enabled: true,
+            ..DlpPolicy::disabled("tenant-1")
+        }
+    }
+
+    #[test]
+    fn test_scan_redacts_and_alerts() {
+        let scanner = policy(vec![
+            rule("cards", DlpDetector::CreditCard, DlpAction::Redact, vec![]),
+            rule("ssn", DlpDetector::Ssn, DlpAction::Alert, vec![]),
+            rule(
+                "codenames",
+                DlpDetector::Dictionary {
+                    terms: vec!["Project Falcon".to_string()],
+                },
+                DlpAction::Redact,
+                vec![DlpChannel::AiPrompt],
+            ),
+        ])
+        .compile()
+        .unwrap();
+
+        let text = "Card 4111 1111 1111 1111, not 1234 5678 9012 3456. SSN 123-45-6789, not 000-12-3456. About project falcon.";
+
+        let result = scanner.scan(DlpChannel::AiPrompt, text);
+        assert_eq!(result.action, Some(DlpAction::Redact));
+        assert!(!result.is_blocked());
+        let counts: Vec<(&str, usize)> = result
+            .findings
+            .iter()
+            .map(|finding| (finding.rule_id.as_str(), finding.match_count))
+            .collect();
+        assert_eq!(counts, vec![("cards", 1), ("ssn", 1), ("codenames", 1)]);
+        assert_eq!(
+            result.text,
+            "Card [REDACTED], not 1234 5678 9012 3456. SSN 123-45-6789, not 000-12-3456. About [REDACTED]."
+        );
+
+        // The dictionary rule only covers prompts
+        let result = scanner.scan(DlpChannel::FileUpload, text);
+        assert!(result.findings.iter().all(|finding| finding.rule_id != "codenames"));
+        assert!(result.text.ends_with("About project falcon."));
+    }
+
+    #[test]
+    fn test_block_wins_and_incident_omits_matches() {
+        let scanner = policy(vec![
+            rule("cards", DlpDetector::CreditCard, DlpAction::Redact, vec![]),
+            rule(
+                "account-ids",
+                DlpDetector::Regex {
+                    pattern: r"ACCT-\d{6}".to_string(),
+                },
+                DlpAction::Block,
+                vec![],
+            ),
+        ])
+        .compile()
+        .unwrap();
+
+        let result = scanner.scan(DlpChannel::FileUpload, "ACCT-123456 paid with 4111111111111111");
+        assert!(result.is_blocked());
+        assert_eq!(result.blocking_rules(), vec!["account-ids"]);
+
+        let incident = result
+            .incident("tenant-1", "file-service", "file", Some("file-1"), Some("user-1"))
+            .unwrap();
+        assert_eq!(incident.action, DLP_INCIDENT_ACTION);
+        assert_eq!(incident.outcome, AuditOutcome::Failure);
+        assert!(!incident.details.to_string().contains("4111"));
+
+        assert!(scanner.scan(DlpChannel::FileUpload, "nothing here").incident("tenant-1", "file-service", "file", None, None).is_none());
+    }
+
+    #[test]
+    fn test_compile_rejects_invalid_rules() {
+        let invalid_pattern = policy(vec![rule(
+            "bad",
+            DlpDetector::Regex {
+                pattern: "(unclosed".to_string(),
+            },
+            DlpAction::Alert,
+            vec![],
+        )]);
+        assert!(invalid_pattern.compile().is_err());
+
+        let empty_dictionary = policy(vec![rule(
+            "empty",
+            DlpDetector::Dictionary {
+                terms: vec![" ".to_string()],
+            },
+            DlpAction::Alert,
+            vec![],
+        )]);
+        assert!(empty_dictionary.compile().is_err());
+
+        let duplicate = policy(vec![
+            rule("same", DlpDetector::Ssn, DlpAction::Alert, vec![]),
+            rule("same", DlpDetector::CreditCard, DlpAction::Alert, vec![]),
+        ]);
+        assert!(duplicate.compile().is_err());
+    }
+
+    #[test]
+    fn test_disabled_policy_scans_nothing() {
+        let scanner = DlpPolicy {
+            rules: vec![rule("ssn", DlpDetector::Ssn, DlpAction::Block, vec![])],
+            ..DlpPolicy::disabled("tenant-1")
+        }
+        .compile()
+        .unwrap();
+
+        let result = scanner.scan(DlpChannel::AiPrompt, "SSN 123-45-6789");
+        assert!(result.findings.is_empty());
+        assert_eq!(result.action, None);
+        assert_eq!(result.text, "SSN 123-45-6789");
+    }
+}
@@ -0,0 +1,428 @@
+// Transactional email
+//
+// Services never talk to a mail server themselves. They ask
+// white-label-service to send one of the platform's system emails, naming
+// the tenant it is sent for; white-label-service renders the tenant's
+// override of the template, or the platform default, with the tenant's
+// published branding and delivers it through the tenant's own SMTP server
+// or SES account when it has configured one. Workflows send through
+// `SendEmailActivity` so retries reuse the same idempotency key and a
+// message is delivered once.
+
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+
+use crate::custom_domains::INTERNAL_TOKEN_HEADER;
+use crate::temporal::activity::utils::external_service_retry_policy;
+use crate::temporal::{ActivityContext, ActivityError, ActivityExecutionOptions, AdxActivity};
+use crate::{Result, ServiceError};
+
+/// The system emails tenants can restyle and reword
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum SystemEmail {
+    EmailVerification,
+    PasswordReset,
+    UserInvitation,
+    PaymentFailed,
+    PaymentOutstanding,
+    AccountReadOnly,
+    AccountSuspended,
+    PaymentSettled,
+}
+
+impl SystemEmail {
+    pub const ALL: [SystemEmail; 8] = [
+        SystemEmail::EmailVerification,
+        SystemEmail::PasswordReset,
+        SystemEmail::UserInvitation,
+        SystemEmail::PaymentFailed,
+        SystemEmail::PaymentOutstanding,
+        SystemEmail::AccountReadOnly,
+        SystemEmail::AccountSuspended,
+        SystemEmail::PaymentSettled,
+    ];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SystemEmail::EmailVerification => "email_verification",
+            SystemEmail::PasswordReset => "password_reset",
+            SystemEmail::UserInvitation => "user_invitation",
+            SystemEmail::PaymentFailed => "payment_failed",
+            SystemEmail::PaymentOutstanding => "payment_outstanding",
+            SystemEmail::AccountReadOnly => "account_read_only",
+            SystemEmail::AccountSuspended => "account_suspended",
+            SystemEmail::PaymentSettled => "payment_settled",
+        }
+    }
+
+    pub fn parse(value: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|email| email.as_str() == value)
+    }
+
+    /// Variables the template is rendered with, besides the branding ones
+    pub fn variables(&self) -> &'static [&'static str] {
+        match self {
+            SystemEmail::EmailVerification => &["user_name", "verification_url", "expires_in_hours"],
+            SystemEmail::PasswordReset => &["user_name", "reset_url", "expires_in_minutes"],
+            SystemEmail::UserInvitation => &["inviter_name", "tenant_name", "invitation_url", "role"],
+            SystemEmail::PaymentFailed => &["amount", "currency", "next_attempt_date"],
+            SystemEmail::PaymentOutstanding => {
+                &["amount", "currency", "attempt_count", "restricted_features", "next_attempt_date"]
+            }
+            SystemEmail::AccountReadOnly => &["amount", "currency", "grace_ends_date"],
+            SystemEmail::AccountSuspended => &["amount", "currency"],
+            SystemEmail::PaymentSettled => &["amount", "currency", "forgiven"],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct EmailRecipient {
+    pub email: String,
+    pub name: Option<String>,
+}
+
+impl EmailRecipient {
+    pub fn new(email: impl Into<String>) -> Self {
+        Self {
+            email: email.into(),
+            name: None,
+        }
+    }
+
+    pub fn named(email: impl Into<String>, name: impl Into<String>) -> Self {
+        Self {
+            email: email.into(),
+            name: Some(name.into()),
+        }
+    }
+}
+
+/// A system email to render and send for a tenant
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SendEmailRequest {
+    /// Tenant whose branding, templates and mail server are used; platform
+    /// emails without a tenant use the platform defaults
+    pub tenant_id: Option<String>,
+    pub template: SystemEmail,
+    pub to: Vec<EmailRecipient>,
+    /// BCP 47 language tag, e.g. "de" or "pt-BR"
+    pub locale: Option<String>,
+    pub variables: serde_json::Map<String, serde_json::Value>,
+    pub tags: Vec<String>,
+    /// A request repeated with the same key is delivered once
+    pub idempotency_key: Option<String>,
+}
+
+impl SendEmailRequest {
+    pub fn new(tenant_id: Option<String>, template: SystemEmail, to: EmailRecipient) -> Self {
+        Self {
+            tenant_id,
+            template,
+            to: vec![to],
+            locale: None,
+            variables: serde_json::Map::new(),
+            tags: Vec::new(),
+            idempotency_key: None,
+        }
+    }
+
+    pub fn variable(mut self, name: &str, value: impl Into<serde_json::Value>) -> Self {
+        self.variables.insert(name.to_string(), value.into());
+        self
+    }
+
+    pub fn locale(mut self, locale: impl Into<String>) -> Self {
+        self.locale = Some(locale.into());
+        self
+    }
+
+    pub fn tag(mut self, tag: impl Into<String>) -> Self {
+        self.tags.push(tag.into());
+        self
+    }
+
+    pub fn idempotency_key(mut self, key: impl Into<String>) -> Self {
+        self.idempotency_key = Some(key.into());
+        self
+    }
+
+    pub fn validate(&self) -> Result<()> {
+        if self.to.is_empty() {
+            return Err(ServiceError::Validation("An email needs at least one recipient".to_string()));
+        }
+        if let Some(recipient) = self.to.iter().find(|recipient| !is_plausible_address(&recipient.email)) {
+            return Err(ServiceError::Validation(format!(
+                "Invalid recipient address: {}",
+                recipient.email
+            )));
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SendEmailResponse {
+    pub delivery_id: String,
+    /// "smtp" or "ses"
+    pub provider: String,
+    pub message_id: Option<String>,
+    /// The idempotency key matched an earlier delivery, which is returned
+    pub duplicate: bool,
+}
+
+fn is_plausible_address(address: &str) -> bool {
+    match address.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty() && domain.contains('.') && !address.chars().any(|c| c.is_whitespace() || c == ',')
+        }
+        None => false,
+    }
+}
+
+/// Where system emails are sent through
+#[async_trait]
+pub trait EmailSender: Send + Sync {
+    async fn send(&self, request: &SendEmailRequest) -> Result<SendEmailResponse>;
+}
+
+/// Sends through white-label-service's internal email endpoint
+pub struct HttpEmailSender {
+    client: reqwest::Client,
+    base_url: String,
+    internal_token: String,
+}
+
+impl HttpEmailSender {
+    pub fn new(base_url: &str, internal_token: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            internal_token: internal_token.to_string(),
+        }
+    }
+
+    /// Sender configured by `WHITE_LABEL_SERVICE_URL` and
+    /// `WHITE_LABEL_INTERNAL_API_TOKEN`
+    pub fn from_env() -> Self {
+        Self::new(
+            &std::env::var("WHITE_LABEL_SERVICE_URL").unwrap_or_else(|_| "http://localhost:8089".to_string()),
+            &std::env::var("WHITE_LABEL_INTERNAL_API_TOKEN").unwrap_or_default(),
+        )
+    }
+}
+
+#[async_trait]
+impl EmailSender for HttpEmailSender {
+    async fn send(&self, request: &SendEmailRequest) -> Result<SendEmailResponse> {
+        let response = self
+            .client
+            .post(format!("{}/api/v1/white-label/internal/emails/send", self.base_url))
+            .header(INTERNAL_TOKEN_HEADER, &self.internal_token)
+            .json(request)
+            .send()
+            .await
+            .map_err(|e| ServiceError::ExternalService(format!("Email request failed: {}", e)))?;
+
+        let status = response.status();
+        if status.is_client_error() {
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(ServiceError::Validation(format!(
+                "Email {} rejected ({}): {}",
+                request.template.as_str(),
+                status,
+                error_text
+            )));
+        }
+        if !status.is_success() {
+            return Err(ServiceError::ExternalService(format!(
+                "Email {} returned {}",
+                request.template.as_str(),
+                status
+            )));
+        }
+
+        response
+            .json::<SendEmailResponse>()
+            .await
+            .map_err(|e| ServiceError::ExternalService(format!("Invalid email response: {}", e)))
+    }
+}
+
+/// The email-sending activity every service's workflows use. Requests
+/// without an idempotency key get the activity id, which Temporal keeps
+/// across retries.
+pub struct SendEmailActivity {
+    sender: Arc<dyn EmailSender>,
+}
+
+impl SendEmailActivity {
+    pub const ACTIVITY_TYPE: &'static str = "send_email";
+
+    pub fn new(sender: Arc<dyn EmailSender>) -> Self {
+        Self { sender }
+    }
+
+    pub fn from_env() -> Self {
+        Self::new(Arc::new(HttpEmailSender::from_env()))
+    }
+}
+
+impl AdxActivity<SendEmailRequest, SendEmailResponse> for SendEmailActivity {
+    async fn execute(&self, context: ActivityContext, mut input: SendEmailRequest) -> std::result::Result<SendEmailResponse, ActivityError> {
+        self.validate_input(&input)?;
+        if input.idempotency_key.is_none() {
+            input.idempotency_key = Some(format!("{}:{}", context.workflow_id, context.activity_id));
+        }
+
+        self.sender.send(&input).await.map_err(|e| match e {
+            ServiceError::Validation(message) => ActivityError::ValidationError {
+                field: "email".to_string(),
+                message,
+            },
+            other => ActivityError::ExternalServiceError {
+                service: "white-label-service".to_string(),
+                message: other.to_string(),
+            },
+        })
+    }
+
+    fn activity_type(&self) -> &'static str {
+        Self::ACTIVITY_TYPE
+    }
+
+    fn default_options(&self) -> ActivityExecutionOptions {
+        ActivityExecutionOptions {
+            retry_policy: Some(external_service_retry_policy()),
+            ..ActivityExecutionOptions::default()
+        }
+    }
+
+    fn validate_input(&self, input: &SendEmailRequest) -> std::result::Result<(), ActivityError> {
+        input.validate().map_err(|e| ActivityError::ValidationError {
+            field: "to".to_string(),
+            message: e.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::Mutex;
+
+    #[derive(Default)]
+    struct RecordingSender {
+        sent: Mutex<Vec<SendEmailRequest>>,
+    }
+
+    #[async_trait]
+    impl EmailSender for RecordingSender {
+        async fn send(&self, request: &SendEmailRequest) -> Result<SendEmailResponse> {
+            self.sent.lock().unwrap().push(request.clone());
+            Ok(SendEmailResponse {
+                delivery_id: "delivery-1".to_string(),
+                provider: "smtp".to_string(),
+                message_id: None,
+                duplicate: false,
+            })
+        }
+    }
+
+    fn context() -> ActivityContext {
+        serde_json::from_value(serde_json::json!({
+            "activity_id": "activity-1",
+            "activity_type": "send_email",
+            "workflow_id": "user-registration-1",
+            "workflow_run_id": "run-1",
+            "attempt": 2,
+            "user_context": {
+                "user_id": "user-1",
+                "email": "ada@example.com",
+                "roles": [],
+                "permissions": [],
+                "session_id": null,
+                "device_info": null
+            },
+            "tenant_context": {
+                "tenant_id": "tenant-1",
+                "tenant_name": "Acme",
+                "subscription_tier": "Professional",
+                "features": [],
+                "quotas": {
+                    "max_users": 10,
+                    "max_storage_gb": 10,
+                    "max_api_calls_per_hour": 1000,
+                    "max_concurrent_workflows": 10,
+                    "max_file_upload_size_mb": 10
+                },
+                "settings": {
+                    "default_language": "en",
+                    "timezone": "UTC",
+                    "date_format": "YYYY-MM-DD",
+                    "currency": "USD",
+                    "branding": null
+                },
+                "isolation_level": "Schema"
+            },
+            "metadata": {
+                "start_time": "2026-01-01T00:00:00Z",
+                "timeout": { "secs": 60, "nanos": 0 },
+                "heartbeat_timeout": null,
+                "retry_policy": null,
+                "tags": [],
+                "custom": {}
+            },
+            "heartbeat_details": null
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_system_email_names_round_trip() {
+        for email in SystemEmail::ALL {
+            assert_eq!(SystemEmail::parse(email.as_str()), Some(email));
+            assert_eq!(serde_json::to_value(email).unwrap(), email.as_str());
+        }
+        assert_eq!(SystemEmail::parse("newsletter"), None);
+    }
+
+    #[test]
+    fn test_validates_recipients() {
+        let request = SendEmailRequest::new(None, SystemEmail::PasswordReset, EmailRecipient::new("ada@example.com"));
+        assert!(request.validate().is_ok());
+
+        let mut invalid = request.clone();
+        invalid.to.clear();
+        assert!(invalid.validate().is_err());
+
+        for address in ["ada", "ada@localhost", "ada@example.com, eve@example.com", "@example.com"] {
+            let invalid = SendEmailRequest::new(None, SystemEmail::PasswordReset, EmailRecipient::new(address));
+            assert!(invalid.validate().is_err(), "{} should be rejected", address);
+        }
+    }
+
+    #[tokio::test]
+    async fn test_activity_keys_retries_by_activity() {
+        let sender = Arc::new(RecordingSender::default());
+        let activity = SendEmailActivity::new(sender.clone());
+
+        let request = SendEmailRequest::new(
+            Some("tenant-1".to_string()),
+            SystemEmail::EmailVerification,
+            EmailRecipient::named("ada@example.com", "Ada"),
+        )
+        .variable("verification_url", "https://app.acme.com/verify?token=abc");
+        activity.execute(context(), request.clone()).await.unwrap();
+        activity
+            .execute(context(), request.idempotency_key("invite-42"))
+            .await
+            .unwrap();
+
+        let sent = sender.sent.lock().unwrap();
+        assert_eq!(sent[0].idempotency_key.as_deref(), Some("user-registration-1:activity-1"));
+        assert_eq!(sent[1].idempotency_key.as_deref(), Some("invite-42"));
+    }
+}
@@ -0,0 +1,421 @@
+// Tenant entitlement client
+//
+// License-service works out what a tenant may use: its plan's features, its
+// quota limits and the marketplace modules it holds. Services and BFFs ask
+// `EntitlementClient` rather than each deciding from the plan themselves;
+// answers are cached per tenant for a short TTL. A tenant whose license
+// lapsed is read-only until its grace period ends, then suspended.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+use crate::{Result, ServiceError};
+
+pub const DEFAULT_ENTITLEMENT_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// A quota's limit and usage when the entitlements were worked out
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QuotaEntitlement {
+    pub quota_name: String,
+    /// -1 means unlimited
+    pub quota_limit: i64,
+    pub current_usage: i64,
+    /// -1 means unlimited
+    pub remaining: i64,
+    pub unit: String,
+}
+
+/// A marketplace module the tenant holds a license for
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ModuleEntitlement {
+    pub module_id: String,
+    pub version: String,
+    /// Installed and switched on
+    pub active: bool,
+}
+
+/// How much of the platform a tenant can use while its license lapses
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum AccessMode {
+    #[default]
+    Full,
+    /// Data can be read but not changed until the grace period ends
+    ReadOnly,
+    Suspended,
+}
+
+impl AccessMode {
+    /// Whether requests with this method may go through
+    pub fn allows_method(&self, method: &str) -> bool {
+        match self {
+            AccessMode::Full => true,
+            AccessMode::ReadOnly => matches!(method, "GET" | "HEAD" | "OPTIONS"),
+            AccessMode::Suspended => false,
+        }
+    }
+}
+
+/// Everything one tenant may use
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Entitlements {
+    pub tenant_id: String,
+    pub subscription_tier: Option<String>,
+    pub license_status: Option<String>,
+    /// Without an active license the tenant is entitled to nothing
+    pub active: bool,
+    pub features: Vec<String>,
+    /// Features the plan has but are switched off while payment is recovered
+    #[serde(default)]
+    pub restricted_features: Vec<String>,
+    #[serde(default)]
+    pub quotas: Vec<QuotaEntitlement>,
+    #[serde(default)]
+    pub modules: Vec<ModuleEntitlement>,
+    #[serde(default)]
+    pub access_mode: AccessMode,
+    /// End of the read-only grace period of a lapsed license
+    #[serde(default)]
+    pub grace_ends_at: Option<DateTime<Utc>>,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl Entitlements {
+    /// Read-only tenants keep their features to look at
+    pub fn has_feature(&self, feature: &str) -> bool {
+        self.entitled() && self.features.iter().any(|f| f == feature)
+    }
+
+    pub fn quota(&self, quota_name: &str) -> Option<&QuotaEntitlement> {
+        self.quotas.iter().find(|quota| quota.quota_name == quota_name)
+    }
+
+    /// The quota's limit; unknown quotas have none to give
+    pub fn quota_limit(&self, quota_name: &str) -> i64 {
+        self.quota(quota_name).map_or(0, |quota| quota.quota_limit)
+    }
+
+    /// Whether the module is licensed and switched on for the tenant
+    pub fn has_module(&self, module_id: &str) -> bool {
+        self.entitled()
+            && self
+                .modules
+                .iter()
+                .any(|module| module.module_id == module_id && module.active)
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.access_mode == AccessMode::ReadOnly
+    }
+
+    fn entitled(&self) -> bool {
+        self.active || self.is_read_only()
+    }
+}
+
+/// Where entitlements come from
+#[async_trait]
+pub trait EntitlementSource: Send + Sync {
+    async fn fetch_entitlements(&self, tenant_id: &str) -> Result<Entitlements>;
+}
+
+#[derive(Deserialize)]
+struct LicenseResponse<T> {
+    data: Option<T>,
+}
+
+/// Reads entitlements from license-service over HTTP
+pub struct HttpEntitlementSource {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl HttpEntitlementSource {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl EntitlementSource for HttpEntitlementSource {
+    async fn fetch_entitlements(&self, tenant_id: &str) -> Result<Entitlements> {
+        let url = format!("{}/api/v1/entitlements/{}", self.base_url, tenant_id);
+
+        let response = self
+            .client
+            .get(&url)
+            .header("X-Tenant-ID", tenant_id)
+            .send()
+            .await
+            .map_err(|e| ServiceError::ExternalService(format!("Entitlement request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(ServiceError::ExternalService(format!(
+                "Entitlement request for tenant {} returned {}",
+                tenant_id,
+                response.status()
+            )));
+        }
+
+        response
+            .json::<LicenseResponse<Entitlements>>()
+            .await
+            .map_err(|e| ServiceError::ExternalService(format!("Invalid entitlement response: {}", e)))?
+            .data
+            .ok_or_else(|| ServiceError::ExternalService("Entitlement response has no data".to_string()))
+    }
+}
+
+struct CachedEntitlements {
+    entitlements: Entitlements,
+    fetched_at: Instant,
+}
+
+/// Cached per-tenant entitlement lookups
+pub struct EntitlementClient {
+    source: Arc<dyn EntitlementSource>,
+    ttl: Duration,
+    cache: RwLock<HashMap<String, CachedEntitlements>>,
+}
+
+impl EntitlementClient {
+    pub fn new(source: Arc<dyn EntitlementSource>) -> Self {
+        Self {
+            source,
+            ttl: DEFAULT_ENTITLEMENT_CACHE_TTL,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Client backed by license-service at `base_url`
+    pub fn from_url(base_url: &str) -> Self {
+        Self::new(Arc::new(HttpEntitlementSource::new(base_url)))
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub async fn entitlements(&self, tenant_id: &str) -> Result<Entitlements> {
+        if let Some(cached) = self.cache.read().await.get(tenant_id) {
+            if cached.fetched_at.elapsed() < self.ttl {
+                return Ok(cached.entitlements.clone());
+            }
+        }
+
+        match self.source.fetch_entitlements(tenant_id).await {
+            Ok(entitlements) => {
+                self.cache.write().await.insert(
+                    tenant_id.to_string(),
+                    CachedEntitlements {
+                        entitlements: entitlements.clone(),
+                        fetched_at: Instant::now(),
+                    },
+                );
+                Ok(entitlements)
+            }
+            Err(e) => {
+                // Serve the last answer rather than failing every check while license-service is unreachable
+                if let Some(cached) = self.cache.read().await.get(tenant_id) {
+                    tracing::warn!(tenant_id = tenant_id, error = %e, "Serving stale entitlements");
+                    return Ok(cached.entitlements.clone());
+                }
+                Err(e)
+            }
+        }
+    }
+
+    pub async fn has_feature(&self, tenant_id: &str, feature: &str) -> Result<bool> {
+        Ok(self.entitlements(tenant_id).await?.has_feature(feature))
+    }
+
+    pub async fn has_module(&self, tenant_id: &str, module_id: &str) -> Result<bool> {
+        Ok(self.entitlements(tenant_id).await?.has_module(module_id))
+    }
+
+    /// Fail with `Authorization` unless the tenant is entitled to the feature
+    pub async fn require_feature(&self, tenant_id: &str, feature: &str) -> Result<()> {
+        if !self.has_feature(tenant_id, feature).await? {
+            return Err(ServiceError::Authorization(format!(
+                "Tenant {} is not entitled to {}",
+                tenant_id, feature
+            )));
+        }
+        Ok(())
+    }
+
+    /// Drop a tenant's cached entitlements, e.g. after a plan change
+    pub async fn invalidate(&self, tenant_id: &str) {
+        self.cache.write().await.remove(tenant_id);
+    }
+
+    pub async fn invalidate_all(&self) {
+        self.cache.write().await.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountingSource {
+        calls: AtomicUsize,
+        fail_after: usize,
+        active: bool,
+        access_mode: AccessMode,
+    }
+
+    #[async_trait]
+    impl EntitlementSource for CountingSource {
+        async fn fetch_entitlements(&self, tenant_id: &str) -> Result<Entitlements> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            if call >= self.fail_after {
+                return Err(ServiceError::ExternalService("unavailable".to_string()));
+            }
+
+            Ok(Entitlements {
+                tenant_id: tenant_id.to_string(),
+                subscription_tier: Some("Professional".to_string()),
+                license_status: Some("Active".to_string()),
+                active: self.active,
+                features: vec!["sso".to_string()],
+                restricted_features: vec!["api_access".to_string()],
+                quotas: vec![QuotaEntitlement {
+                    quota_name: "users_per_tenant".to_string(),
+                    quota_limit: 10,
+                    current_usage: 4,
+                    remaining: 6,
+                    unit: "users".to_string(),
+                }],
+                modules: vec![
+                    ModuleEntitlement {
+                        module_id: "crm".to_string(),
+                        version: "1.2.0".to_string(),
+                        active: true,
+                    },
+                    ModuleEntitlement {
+                        module_id: "reports".to_string(),
+                        version: "0.9.0".to_string(),
+                        active: false,
+                    },
+                ],
+                access_mode: self.access_mode,
+                grace_ends_at: None,
+                generated_at: Utc::now(),
+            })
+        }
+    }
+
+    fn source(fail_after: usize, active: bool) -> Arc<CountingSource> {
+        Arc::new(CountingSource {
+            calls: AtomicUsize::new(0),
+            fail_after,
+            active,
+            access_mode: AccessMode::Full,
+        })
+    }
+
+    fn lapsed_source(active: bool, access_mode: AccessMode) -> Arc<CountingSource> {
+        Arc::new(CountingSource {
+            calls: AtomicUsize::new(0),
+            fail_after: usize::MAX,
+            active,
+            access_mode,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_entitlements_are_cached_per_tenant() {
+        let source = source(usize::MAX, true);
+        let client = EntitlementClient::new(source.clone());
+
+        assert!(client.has_feature("tenant-1", "sso").await.unwrap());
+        assert!(!client.has_feature("tenant-1", "api_access").await.unwrap());
+        assert!(client.has_module("tenant-1", "crm").await.unwrap());
+        assert!(!client.has_module("tenant-1", "reports").await.unwrap());
+        assert_eq!(source.calls.load(Ordering::SeqCst), 1);
+
+        client.has_feature("tenant-2", "sso").await.unwrap();
+        assert_eq!(source.calls.load(Ordering::SeqCst), 2);
+
+        client.invalidate("tenant-1").await;
+        client.has_feature("tenant-1", "sso").await.unwrap();
+        assert_eq!(source.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_stale_entitlements_served_when_source_fails() {
+        let client = EntitlementClient::new(source(1, true)).with_ttl(Duration::ZERO);
+
+        assert!(client.has_feature("tenant-1", "sso").await.unwrap());
+        // The refresh fails, so the previous answer is reused
+        assert!(client.has_feature("tenant-1", "sso").await.unwrap());
+        assert!(client.has_feature("tenant-2", "sso").await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_inactive_tenant_is_entitled_to_nothing() {
+        let client = EntitlementClient::new(source(usize::MAX, false));
+
+        assert!(!client.has_module("tenant-1", "crm").await.unwrap());
+        let err = client.require_feature("tenant-1", "sso").await.unwrap_err();
+        assert!(matches!(err, ServiceError::Authorization(_)));
+    }
+
+    #[tokio::test]
+    async fn test_quota_limits() {
+        let client = EntitlementClient::new(source(usize::MAX, true));
+        let entitlements = client.entitlements("tenant-1").await.unwrap();
+
+        assert_eq!(entitlements.quota_limit("users_per_tenant"), 10);
+        assert_eq!(entitlements.quota("users_per_tenant").unwrap().remaining, 6);
+        assert_eq!(entitlements.quota_limit("unknown"), 0);
+    }
+
+    #[tokio::test]
+    async fn test_read_only_tenant_keeps_features_but_not_writes() {
+        let client = EntitlementClient::new(lapsed_source(false, AccessMode::ReadOnly));
+        let entitlements = client.entitlements("tenant-1").await.unwrap();
+
+        assert!(entitlements.is_read_only());
+        assert!(entitlements.has_feature("sso"));
+        assert!(entitlements.has_module("crm"));
+        assert!(entitlements.access_mode.allows_method("GET"));
+        assert!(!entitlements.access_mode.allows_method("POST"));
+    }
+
+    #[tokio::test]
+    async fn test_suspended_tenant_is_entitled_to_nothing() {
+        let client = EntitlementClient::new(lapsed_source(false, AccessMode::Suspended));
+        let entitlements = client.entitlements("tenant-1").await.unwrap();
+
+        assert!(!entitlements.has_feature("sso"));
+        assert!(!entitlements.access_mode.allows_method("GET"));
+    }
+
+    #[test]
+    fn test_access_mode_defaults_to_full() {
+        let entitlements: Entitlements = serde_json::from_value(serde_json::json!({
+            "tenant_id": "tenant-1",
+            "subscription_tier": null,
+            "license_status": null,
+            "active": true,
+            "features": [],
+            "generated_at": "2024-01-01T00:00:00Z"
+        }))
+        .unwrap();
+
+        assert_eq!(entitlements.access_mode, AccessMode::Full);
+        assert!(entitlements.access_mode.allows_method("DELETE"));
+    }
+}
@@ -27,6 +27,13 @@ pub enum ServiceError {
     #[error("Temporal workflow error: {0}")]
     Workflow(String),
     
+    #[error("Quota exceeded: {0}")]
+    QuotaExceeded(String),
+
+    /// Content refused by a tenant policy, with a code clients can act on
+    #[error("Content rejected: {message}")]
+    ContentRejected { code: &'static str, message: String },
+    
     #[error("External service error: {0}")]
     ExternalService(String),
     
@@ -51,6 +58,8 @@ impl ServiceError {
             ServiceError::Authorization(_) => 403,
             ServiceError::Validation(_) => 400,
             ServiceError::Tenant(_) => 404,
+            ServiceError::QuotaExceeded(_) => 429,
+            ServiceError::ContentRejected { .. } => 415,
             _ => 500,
         }
     }
@@ -65,6 +74,11 @@ mod tests {
         assert_eq!(ServiceError::Authentication("test".to_string()).status_code(), 401);
         assert_eq!(ServiceError::Authorization("test".to_string()).status_code(), 403);
         assert_eq!(ServiceError::Validation("test".to_string()).status_code(), 400);
+        assert_eq!(ServiceError::QuotaExceeded("test".to_string()).status_code(), 429);
+        assert_eq!(
+            ServiceError::ContentRejected { code: "FILE_TYPE_BLOCKED", message: "test".to_string() }.status_code(),
+            415
+        );
         assert_eq!(ServiceError::Internal("test".to_string()).status_code(), 500);
     }
 
@@ -0,0 +1,246 @@
+// Feature flag evaluation client
+//
+// Flags are defined and evaluated by tenant-service. Services and BFFs use
+// `FeatureFlagClient` to read a tenant's evaluated flags; results are cached
+// per tenant for a short TTL so flag checks stay off the request path.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+use crate::{Result, ServiceError};
+
+pub const DEFAULT_FLAG_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Every flag evaluated for one tenant
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct FeatureFlagSet {
+    pub tenant_id: String,
+    pub flags: HashMap<String, bool>,
+    pub evaluated_at: DateTime<Utc>,
+}
+
+impl FeatureFlagSet {
+    pub fn new(tenant_id: &str, flags: HashMap<String, bool>) -> Self {
+        Self {
+            tenant_id: tenant_id.to_string(),
+            flags,
+            evaluated_at: Utc::now(),
+        }
+    }
+
+    /// Unknown flags are off
+    pub fn is_enabled(&self, key: &str) -> bool {
+        self.flags.get(key).copied().unwrap_or(false)
+    }
+
+    /// Keys of enabled flags, sorted
+    pub fn enabled_keys(&self) -> Vec<String> {
+        let mut keys: Vec<String> = self
+            .flags
+            .iter()
+            .filter(|(_, enabled)| **enabled)
+            .map(|(key, _)| key.clone())
+            .collect();
+        keys.sort();
+        keys
+    }
+}
+
+/// Where evaluated flags come from
+#[async_trait]
+pub trait FeatureFlagSource: Send + Sync {
+    async fn fetch_flags(&self, tenant_id: &str) -> Result<FeatureFlagSet>;
+}
+
+/// Reads evaluated flags from tenant-service over HTTP
+pub struct HttpFeatureFlagSource {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl HttpFeatureFlagSource {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl FeatureFlagSource for HttpFeatureFlagSource {
+    async fn fetch_flags(&self, tenant_id: &str) -> Result<FeatureFlagSet> {
+        let url = format!("{}/api/v1/tenants/{}/feature-flags", self.base_url, tenant_id);
+
+        let response = self
+            .client
+            .get(&url)
+            .header("X-Tenant-ID", tenant_id)
+            .send()
+            .await
+            .map_err(|e| ServiceError::ExternalService(format!("Feature flag request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(ServiceError::ExternalService(format!(
+                "Feature flag request for tenant {} returned {}",
+                tenant_id,
+                response.status()
+            )));
+        }
+
+        response
+            .json::<FeatureFlagSet>()
+            .await
+            .map_err(|e| ServiceError::ExternalService(format!("Invalid feature flag response: {}", e)))
+    }
+}
+
+struct CachedFlags {
+    flags: FeatureFlagSet,
+    fetched_at: Instant,
+}
+
+/// Cached per-tenant flag lookups
+pub struct FeatureFlagClient {
+    source: Arc<dyn FeatureFlagSource>,
+    ttl: Duration,
+    cache: RwLock<HashMap<String, CachedFlags>>,
+}
+
+impl FeatureFlagClient {
+    pub fn new(source: Arc<dyn FeatureFlagSource>) -> Self {
+        Self {
+            source,
+            ttl: DEFAULT_FLAG_CACHE_TTL,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Client backed by tenant-service at `base_url`
+    pub fn from_url(base_url: &str) -> Self {
+        Self::new(Arc::new(HttpFeatureFlagSource::new(base_url)))
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub async fn flags(&self, tenant_id: &str) -> Result<FeatureFlagSet> {
+        if let Some(cached) = self.cache.read().await.get(tenant_id) {
+            if cached.fetched_at.elapsed() < self.ttl {
+                return Ok(cached.flags.clone());
+            }
+        }
+
+        match self.source.fetch_flags(tenant_id).await {
+            Ok(flags) => {
+                self.cache.write().await.insert(
+                    tenant_id.to_string(),
+                    CachedFlags {
+                        flags: flags.clone(),
+                        fetched_at: Instant::now(),
+                    },
+                );
+                Ok(flags)
+            }
+            Err(e) => {
+                // Serve stale flags rather than failing every check while tenant-service is unreachable
+                if let Some(cached) = self.cache.read().await.get(tenant_id) {
+                    tracing::warn!(tenant_id = tenant_id, error = %e, "Serving stale feature flags");
+                    return Ok(cached.flags.clone());
+                }
+                Err(e)
+            }
+        }
+    }
+
+    pub async fn is_enabled(&self, tenant_id: &str, key: &str) -> Result<bool> {
+        Ok(self.flags(tenant_id).await?.is_enabled(key))
+    }
+
+    /// Drop a tenant's cached flags, e.g. after an override changes
+    pub async fn invalidate(&self, tenant_id: &str) {
+        self.cache.write().await.remove(tenant_id);
+    }
+
+    pub async fn invalidate_all(&self) {
+        self.cache.write().await.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    struct CountingSource {
+        calls: AtomicUsize,
+        fail_after: usize,
+    }
+
+    #[async_trait]
+    impl FeatureFlagSource for CountingSource {
+        async fn fetch_flags(&self, tenant_id: &str) -> Result<FeatureFlagSet> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            if call >= self.fail_after {
+                return Err(ServiceError::ExternalService("unavailable".to_string()));
+            }
+
+            let mut flags = HashMap::new();
+            flags.insert("sso".to_string(), true);
+            flags.insert("beta_editor".to_string(), false);
+            Ok(FeatureFlagSet::new(tenant_id, flags))
+        }
+    }
+
+    fn source(fail_after: usize) -> Arc<CountingSource> {
+        Arc::new(CountingSource {
+            calls: AtomicUsize::new(0),
+            fail_after,
+        })
+    }
+
+    #[tokio::test]
+    async fn test_flags_are_cached_per_tenant() {
+        let source = source(usize::MAX);
+        let client = FeatureFlagClient::new(source.clone());
+
+        assert!(client.is_enabled("tenant-1", "sso").await.unwrap());
+        assert!(!client.is_enabled("tenant-1", "beta_editor").await.unwrap());
+        assert!(!client.is_enabled("tenant-1", "unknown").await.unwrap());
+        assert_eq!(source.calls.load(Ordering::SeqCst), 1);
+
+        client.is_enabled("tenant-2", "sso").await.unwrap();
+        assert_eq!(source.calls.load(Ordering::SeqCst), 2);
+
+        client.invalidate("tenant-1").await;
+        client.is_enabled("tenant-1", "sso").await.unwrap();
+        assert_eq!(source.calls.load(Ordering::SeqCst), 3);
+    }
+
+    #[tokio::test]
+    async fn test_stale_flags_served_when_source_fails() {
+        let client = FeatureFlagClient::new(source(1)).with_ttl(Duration::ZERO);
+
+        assert!(client.is_enabled("tenant-1", "sso").await.unwrap());
+        // The refresh fails, so the previous result is reused
+        assert!(client.is_enabled("tenant-1", "sso").await.unwrap());
+        assert!(client.is_enabled("tenant-2", "sso").await.is_err());
+    }
+
+    #[test]
+    fn test_enabled_keys_sorted() {
+        let mut flags = HashMap::new();
+        flags.insert("b".to_string(), true);
+        flags.insert("a".to_string(), true);
+        flags.insert("c".to_string(), false);
+
+        assert_eq!(FeatureFlagSet::new("t", flags).enabled_keys(), vec!["a", "b"]);
+    }
+}
@@ -4,9 +4,24 @@
 pub mod database;
 pub mod temporal;
 pub mod auth;
+pub mod audit;
+pub mod dlp;
+pub mod email;
+pub mod login_page;
+pub mod context_token;
+pub mod custom_domains;
 pub mod tenant;
 pub mod error;
 pub mod config;
+pub mod feature_flags;
+pub mod entitlements;
+pub mod network_policy;
+pub mod permissions;
+pub mod pii;
+pub mod quotas;
+pub mod signing_keys;
+pub mod telemetry;
+pub mod types;
 
 // Re-export commonly used types
 pub use error::{Result, ServiceError};
@@ -0,0 +1,716 @@
+// Hosted login pages
+//
+// auth-service serves sign-in and registration pages for tenants that do
+// not build their own. A white-label tenant arranges the page from layout
+// blocks, adds its legal links and SSO button labels and picks the locales
+// it is offered in; white-label-service keeps that configuration as a draft
+// until it is published, and renders previews of it with `render_page`,
+// the same renderer auth-service uses for the live pages it builds from
+// `LoginPageClient::published_for_domain`.
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+use crate::{Result, ServiceError};
+
+const MAX_BLOCKS: usize = 20;
+const MAX_LEGAL_LINKS: usize = 10;
+const MAX_TEXT_LENGTH: usize = 2000;
+const MAX_LABEL_LENGTH: usize = 100;
+
+/// Locales the page's own labels are translated into
+pub const SUPPORTED_LOCALES: [&str; 4] = ["en", "es", "fr", "de"];
+
+/// SSO providers a login page may offer buttons for
+pub const SSO_PROVIDERS: [&str; 6] = ["google", "microsoft", "okta", "auth0", "saml", "oidc"];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum LoginLayout {
+    /// The blocks in a card in the middle of the page
+    #[default]
+    Centered,
+    /// The blocks on one side and the brand colour with the logo on the other
+    Split,
+}
+
+/// Text shown as written, or in one of its translations when the page is
+/// rendered in that locale
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct LocalizedText {
+    pub text: String,
+    #[serde(default)]
+    pub translations: BTreeMap<String, String>,
+}
+
+impl LocalizedText {
+    pub fn new(text: impl Into<String>) -> Self {
+        Self {
+            text: text.into(),
+            translations: BTreeMap::new(),
+        }
+    }
+
+    pub fn get(&self, locale: &str) -> &str {
+        self.translations
+            .get(locale)
+            .or_else(|| self.translations.get(language(locale)))
+            .unwrap_or(&self.text)
+    }
+
+    fn validate(&self, field: &str, max_length: usize) -> Result<()> {
+        for text in std::iter::once(&self.text).chain(self.translations.values()) {
+            if text.trim().is_empty() || text.len() > max_length {
+                return Err(ServiceError::Validation(format!(
+                    "{} must be 1 to {} characters",
+                    field, max_length
+                )));
+            }
+        }
+        self.translations.keys().try_for_each(|locale| validate_locale(locale))
+    }
+}
+
+/// One part of the page, shown in the configured order
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum LoginBlock {
+    Heading { text: LocalizedText },
+    Text { text: LocalizedText },
+    Image { url: String, alt: LocalizedText },
+    /// The sign-in or registration form
+    AuthForm,
+    SsoButtons,
+    LegalLinks,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LegalLink {
+    pub label: LocalizedText,
+    pub url: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct SsoButton {
+    /// One of `SSO_PROVIDERS`
+    pub provider: String,
+    pub label: LocalizedText,
+}
+
+/// How a tenant's hosted login and registration pages look
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LoginPageConfig {
+    #[serde(default)]
+    pub layout: LoginLayout,
+    pub blocks: Vec<LoginBlock>,
+    #[serde(default)]
+    pub legal_links: Vec<LegalLink>,
+    #[serde(default)]
+    pub sso_buttons: Vec<SsoButton>,
+    /// Locale used when the visitor asks for none the page is offered in
+    pub default_locale: String,
+    /// Locales the page is offered in; empty for just the default
+    #[serde(default)]
+    pub locales: Vec<String>,
+    /// Show the registration page and link to it
+    #[serde(default = "default_true")]
+    pub allow_registration: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for LoginPageConfig {
+    fn default() -> Self {
+        Self {
+            layout: LoginLayout::Centered,
+            blocks: vec![LoginBlock::AuthForm, LoginBlock::SsoButtons, LoginBlock::LegalLinks],
+            legal_links: Vec::new(),
+            sso_buttons: Vec::new(),
+            default_locale: "en".to_string(),
+            locales: Vec::new(),
+            allow_registration: true,
+        }
+    }
+}
+
+impl LoginPageConfig {
+    pub fn validate(&self) -> Result<()> {
+        if self.blocks.len() > MAX_BLOCKS {
+            return Err(ServiceError::Validation(format!("A login page has at most {} blocks", MAX_BLOCKS)));
+        }
+        let forms = self.blocks.iter().filter(|block| **block == LoginBlock::AuthForm).count();
+        if forms != 1 {
+            return Err(ServiceError::Validation(
+                "A login page needs exactly one auth_form block".to_string(),
+            ));
+        }
+        for block in &self.blocks {
+            match block {
+                LoginBlock::Heading { text } => text.validate("Heading", MAX_LABEL_LENGTH)?,
+                LoginBlock::Text { text } => text.validate("Text", MAX_TEXT_LENGTH)?,
+                LoginBlock::Image { url, alt } => {
+                    validate_url(url)?;
+                    alt.validate("Image alt text", MAX_LABEL_LENGTH)?;
+                }
+                LoginBlock::AuthForm | LoginBlock::SsoButtons | LoginBlock::LegalLinks => {}
+            }
+        }
+
+        if self.legal_links.len() > MAX_LEGAL_LINKS {
+            return Err(ServiceError::Validation(format!(
+                "A login page has at most {} legal links",
+                MAX_LEGAL_LINKS
+            )));
+        }
+        for link in &self.legal_links {
+            link.label.validate("Legal link label", MAX_LABEL_LENGTH)?;
+            validate_url(&link.url)?;
+        }
+
+        for (index, button) in self.sso_buttons.iter().enumerate() {
+            if !SSO_PROVIDERS.contains(&button.provider.as_str()) {
+                return Err(ServiceError::Validation(format!("Unknown SSO provider {}", button.provider)));
+            }
+            if self.sso_buttons[..index].iter().any(|other| other.provider == button.provider) {
+                return Err(ServiceError::Validation(format!("Duplicate SSO provider {}", button.provider)));
+            }
+            button.label.validate("SSO button label", MAX_LABEL_LENGTH)?;
+        }
+
+        validate_locale(&self.default_locale)?;
+        self.locales.iter().try_for_each(|locale| validate_locale(locale))
+    }
+
+    /// The locale to render in: the first of the visitor's `preferred`
+    /// locales, or its language, the page is offered in, else the default
+    pub fn resolve_locale<S: AsRef<str>>(&self, preferred: impl IntoIterator<Item = S>) -> &str {
+        let offered: Vec<&str> = std::iter::once(self.default_locale.as_str())
+            .chain(self.locales.iter().map(String::as_str))
+            .collect();
+        for locale in preferred {
+            let locale = locale.as_ref().trim().to_ascii_lowercase();
+            if let Some(found) = offered
+                .iter()
+                .find(|offered| **offered == locale)
+                .or_else(|| offered.iter().find(|offered| **offered == language(&locale)))
+            {
+                return found;
+            }
+        }
+        &self.default_locale
+    }
+}
+
+/// The tenant's published theme, as far as the login page uses it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct LoginPageBranding {
+    pub brand_name: String,
+    pub logo_url: Option<String>,
+    pub favicon_url: Option<String>,
+    pub primary_color: String,
+    pub background_color: String,
+    pub text_color: String,
+    pub font_family: String,
+}
+
+impl Default for LoginPageBranding {
+    fn default() -> Self {
+        Self {
+            brand_name: "ADX Core".to_string(),
+            logo_url: None,
+            favicon_url: None,
+            primary_color: "#2563eb".to_string(),
+            background_color: "#f8fafc".to_string(),
+            text_color: "#0f172a".to_string(),
+            font_family: "Inter, system-ui, sans-serif".to_string(),
+        }
+    }
+}
+
+/// A domain's published login page, as auth-service renders it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct HostedLoginPage {
+    pub tenant_id: Option<String>,
+    pub version: i32,
+    pub config: LoginPageConfig,
+    pub branding: LoginPageBranding,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum AuthPage {
+    Login,
+    Register,
+}
+
+/// Request-specific parts of a rendered page
+#[derive(Debug, Clone, Default)]
+pub struct RenderContext {
+    pub locale: String,
+    /// Where the form posts to, e.g. "/api/v1/auth"
+    pub api_base: String,
+    /// Path within the site to go to after signing in
+    pub redirect_path: Option<String>,
+    /// Nonce the page's style and script elements carry for the response's
+    /// Content-Security-Policy
+    pub nonce: String,
+    /// Render for a preview: the form does not submit
+    pub preview: bool,
+}
+
+/// Render `page` as a complete HTML document. Everything taken from the
+/// configuration is escaped, and colours that are not hex colours are
+/// replaced with the platform's.
+pub fn render_page(page: &HostedLoginPage, kind: AuthPage, context: &RenderContext) -> String {
+    let config = &page.config;
+    let branding = &page.branding;
+    let locale = context.locale.as_str();
+    let labels = Labels::for_locale(locale);
+    let defaults = LoginPageBranding::default();
+
+    let mut body = String::new();
+    for block in &config.blocks {
+        match block {
+            LoginBlock::Heading { text } => body.push_str(&format!("<h1>{}</h1>", escape(text.get(locale)))),
+            LoginBlock::Text { text } => body.push_str(&format!("<p class=\"text\">{}</p>", escape(text.get(locale)))),
+            LoginBlock::Image { url, alt } => body.push_str(&format!(
+                "<img class=\"image\" src=\"{}\" alt=\"{}\">",
+                escape(url),
+                escape(alt.get(locale))
+            )),
+            LoginBlock::AuthForm => body.push_str(&auth_form(kind, config.allow_registration, &labels, context)),
+            LoginBlock::SsoButtons if !config.sso_buttons.is_empty() => {
+                body.push_str("<div class=\"sso\">");
+                for button in &config.sso_buttons {
+                    body.push_str(&format!(
+                        "<a class=\"button secondary\" href=\"{}/sso/{}{}\">{}</a>",
+                        escape(&context.api_base),
+                        escape(&button.provider),
+                        redirect_query(context),
+                        escape(button.label.get(locale))
+                    ));
+                }
+                body.push_str("</div>");
+            }
+            LoginBlock::LegalLinks if !config.legal_links.is_empty() => {
+                body.push_str("<nav class=\"legal\">");
+                for link in &config.legal_links {
+                    body.push_str(&format!(
+                        "<a href=\"{}\" rel=\"noopener\" target=\"_blank\">{}</a>",
+                        escape(&link.url),
+                        escape(link.label.get(locale))
+                    ));
+                }
+                body.push_str("</nav>");
+            }
+            LoginBlock::SsoButtons | LoginBlock::LegalLinks => {}
+        }
+    }
+
+    let logo = branding
+        .logo_url
+        .as_ref()
+        .map(|url| format!("<img class=\"logo\" src=\"{}\" alt=\"{}\">", escape(url), escape(&branding.brand_name)))
+        .unwrap_or_else(|| format!("<div class=\"brand\">{}</div>", escape(&branding.brand_name)));
+    let main = match config.layout {
+        LoginLayout::Centered => format!("<main class=\"centered\"><div class=\"card\">{}{}</div></main>", logo, body),
+        LoginLayout::Split => format!(
+            "<main class=\"split\"><aside>{}</aside><div class=\"card\">{}</div></main>",
+            logo, body
+        ),
+    };
+    let favicon = branding
+        .favicon_url
+        .as_ref()
+        .map(|url| format!("<link rel=\"icon\" href=\"{}\">", escape(url)))
+        .unwrap_or_default();
+    let script = if context.preview {
+        String::new()
+    } else {
+        format!("<script nonce=\"{}\">{}</script>", escape(&context.nonce), FORM_SCRIPT)
+    };
+
+    format!(
+        "<!DOCTYPE html><html lang=\"{lang}\"><head><meta charset=\"utf-8\">\
+         <meta name=\"viewport\" content=\"width=device-width, initial-scale=1\">\
+         <title>{title} - {brand}</title>{favicon}\
+         <style nonce=\"{nonce}\">:root{{--primary:{primary};--background:{background};--text:{text};--font:{font}}}{css}</style>\
+         </head><body>{main}{script}</body></html>",
+        lang = escape(locale),
+        title = match kind {
+            AuthPage::Login => labels.sign_in,
+            AuthPage::Register => labels.create_account,
+        },
+        brand = escape(&branding.brand_name),
+        favicon = favicon,
+        nonce = escape(&context.nonce),
+        primary = hex_color(&branding.primary_color, &defaults.primary_color),
+        background = hex_color(&branding.background_color, &defaults.background_color),
+        text = hex_color(&branding.text_color, &defaults.text_color),
+        font = css_font(&branding.font_family, &defaults.font_family),
+        css = PAGE_CSS,
+        main = main,
+        script = script,
+    )
+}
+
+fn auth_form(kind: AuthPage, allow_registration: bool, labels: &Labels, context: &RenderContext) -> String {
+    let (action, title, submit, fields) = match kind {
+        AuthPage::Login => (
+            "login",
+            labels.sign_in,
+            labels.sign_in,
+            format!(
+                "<label>{}<input type=\"email\" name=\"email\" autocomplete=\"username\" required></label>\
+                 <label>{}<input type=\"password\" name=\"password\" autocomplete=\"current-password\" required></label>",
+                labels.email, labels.password
+            ),
+        ),
+        AuthPage::Register => (
+            "register",
+            labels.create_account,
+            labels.create_account,
+            format!(
+                "<label>{}<input type=\"text\" name=\"display_name\" autocomplete=\"name\"></label>\
+                 <label>{}<input type=\"email\" name=\"email\" autocomplete=\"email\" required></label>\
+                 <label>{}<input type=\"password\" name=\"password\" autocomplete=\"new-password\" minlength=\"8\" required></label>",
+                labels.name, labels.email, labels.password
+            ),
+        ),
+    };
+
+    let switch = match kind {
+        AuthPage::Login if allow_registration => format!(
+            "<p class=\"switch\"><a href=\"register{}\">{}</a></p>",
+            redirect_query(context),
+            labels.no_account
+        ),
+        AuthPage::Register => format!(
+            "<p class=\"switch\"><a href=\"login{}\">{}</a></p>",
+            redirect_query(context),
+            labels.have_account
+        ),
+        AuthPage::Login => String::new(),
+    };
+
+    format!(
+        "<form class=\"auth\" data-action=\"{}/{}\" data-redirect=\"{}\"{}><h2>{}</h2>{}\
+         <p class=\"error\" role=\"alert\" hidden></p><button class=\"button\" type=\"submit\">{}</button></form>{}",
+        escape(&context.api_base),
+        action,
+        escape(context.redirect_path.as_deref().unwrap_or("/")),
+        if context.preview { " data-preview" } else { "" },
+        title,
+        fields,
+        submit,
+        switch
+    )
+}
+
+fn redirect_query(context: &RenderContext) -> String {
+    context
+        .redirect_path
+        .as_ref()
+        .map(|path| format!("?redirect={}", urlencode(path)))
+        .unwrap_or_default()
+}
+
+/// A path within the site, which is all a login page redirects to after
+/// signing in
+pub fn is_safe_redirect(path: &str) -> bool {
+    path.starts_with('/') && !path.starts_with("//") && !path.contains('\\') && !path.chars().any(char::is_control)
+}
+
+/// Fetches published login pages from white-label-service's public
+/// branding endpoint
+pub struct LoginPageClient {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl LoginPageClient {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Client configured by `WHITE_LABEL_SERVICE_URL`
+    pub fn from_env() -> Self {
+        Self::new(&std::env::var("WHITE_LABEL_SERVICE_URL").unwrap_or_else(|_| "http://localhost:8089".to_string()))
+    }
+
+    /// The published login page of a verified custom domain; None for other
+    /// domains and tenants that have not published one
+    pub async fn published_for_domain(&self, domain: &str) -> Result<Option<HostedLoginPage>> {
+        let response = self
+            .client
+            .get(format!("{}/api/v1/branding/{}/login-page", self.base_url, urlencode(domain)))
+            .send()
+            .await
+            .map_err(|e| ServiceError::ExternalService(format!("Login page request failed: {}", e)))?;
+
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        if !response.status().is_success() {
+            return Err(ServiceError::ExternalService(format!(
+                "Login page request returned {}",
+                response.status()
+            )));
+        }
+
+        response
+            .json::<HostedLoginPage>()
+            .await
+            .map(Some)
+            .map_err(|e| ServiceError::ExternalService(format!("Invalid login page response: {}", e)))
+    }
+}
+
+struct Labels {
+    sign_in: &'static str,
+    create_account: &'static str,
+    email: &'static str,
+    password: &'static str,
+    name: &'static str,
+    no_account: &'static str,
+    have_account: &'static str,
+}
+
+impl Labels {
+    fn for_locale(locale: &str) -> Self {
+        match language(locale) {
+            "es" => Self {
+                sign_in: "Iniciar sesión",
+                create_account: "Crear cuenta",
+                email: "Correo electrónico",
+                password: "Contraseña",
+                name: "Nombre",
+                no_account: "¿No tienes cuenta? Regístrate",
+                have_account: "¿Ya tienes cuenta? Inicia sesión",
+            },
+            "fr" => Self {
+                sign_in: "Se connecter",
+                create_account: "Créer un compte",
+                email: "E-mail",
+                password: "Mot de passe",
+                name: "Nom",
+                no_account: "Pas de compte ? Inscrivez-vous",
+                have_account: "Déjà un compte ? Connectez-vous",
+            },
+            "de" => Self {
+                sign_in: "Anmelden",
+                create_account: "Konto erstellen",
+                email: "E-Mail",
+                password: "Passwort",
+                name: "Name",
+                no_account: "Noch kein Konto? Registrieren",
+                have_account: "Schon ein Konto? Anmelden",
+            },
+            _ => Self {
+                sign_in: "Sign in",
+                create_account: "Create account",
+                email: "Email",
+                password: "Password",
+                name: "Name",
+                no_account: "No account? Sign up",
+                have_account: "Already have an account? Sign in",
+            },
+        }
+    }
+}
+
+/// Submits the form as JSON, keeps the session for the shell and goes on
+/// to the redirect path
+const FORM_SCRIPT: &str = r#"document.querySelectorAll("form.auth").forEach(function(form){form.addEventListener("submit",function(event){event.preventDefault();if(form.hasAttribute("data-preview"))return;var error=form.querySelector(".error");error.hidden=true;var body={};new FormData(form).forEach(function(value,key){if(value!=="")body[key]=value;});fetch(form.dataset.action,{method:"POST",headers:{"Content-Type":"application/json"},body:JSON.stringify(body)}).then(function(response){return response.json().then(function(data){if(!response.ok)throw new Error((data.error&&data.error.message)||response.statusText);return data;});}).then(function(data){if(data.token){localStorage.setItem("adx.auth",JSON.stringify(data));}window.location.assign(form.dataset.redirect);}).catch(function(e){error.textContent=e.message;error.hidden=false;});});});"#;
+
+const PAGE_CSS: &str = "*{box-sizing:border-box}body{margin:0;font-family:var(--font);color:var(--text);background:var(--background)}\
+main{min-height:100vh;display:flex}main.centered{align-items:center;justify-content:center}\
+main.split aside{flex:1;display:flex;align-items:center;justify-content:center;background:var(--primary)}\
+main.split .card{flex:1;max-width:none;border-radius:0;box-shadow:none}\
+.card{width:100%;max-width:420px;padding:32px;background:#fff;border-radius:12px;box-shadow:0 10px 30px rgba(0,0,0,.08);display:flex;flex-direction:column;gap:16px}\
+.logo{max-height:48px;max-width:200px}.brand{font-size:24px;font-weight:700}.image{max-width:100%}\
+h1{font-size:24px;margin:0}h2{font-size:20px;margin:0}.text{margin:0}\
+form.auth{display:flex;flex-direction:column;gap:12px}label{display:flex;flex-direction:column;gap:4px;font-size:14px}\
+input{padding:10px;border:1px solid #cbd5e1;border-radius:6px;font:inherit}\
+.button{display:block;padding:10px;border:0;border-radius:6px;background:var(--primary);color:#fff;font:inherit;text-align:center;text-decoration:none;cursor:pointer}\
+.button.secondary{background:#fff;color:var(--text);border:1px solid #cbd5e1}.sso{display:flex;flex-direction:column;gap:8px}\
+.error{color:#b91c1c;margin:0}.switch{margin:0;font-size:14px}.legal{display:flex;flex-wrap:wrap;gap:12px;font-size:12px}\
+a{color:var(--primary)}";
+
+fn validate_url(url: &str) -> Result<()> {
+    if url.len() <= 2048 && (url.starts_with("https://") || is_safe_redirect(url)) {
+        Ok(())
+    } else {
+        Err(ServiceError::Validation(format!("{} must be an https URL or a path", url)))
+    }
+}
+
+fn validate_locale(locale: &str) -> Result<()> {
+    let valid = !locale.is_empty()
+        && locale.len() <= 35
+        && locale
+            .split('-')
+            .all(|part| !part.is_empty() && part.len() <= 8 && part.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit()));
+    if valid {
+        Ok(())
+    } else {
+        Err(ServiceError::Validation(format!("Invalid locale {}, expected e.g. en or pt-br", locale)))
+    }
+}
+
+fn language(locale: &str) -> &str {
+    locale.split('-').next().unwrap_or(locale)
+}
+
+fn hex_color<'a>(color: &'a str, fallback: &'a str) -> &'a str {
+    let valid = color.len() == 7 && color.starts_with('#') && color[1..].chars().all(|c| c.is_ascii_hexdigit());
+    if valid {
+        color
+    } else {
+        fallback
+    }
+}
+
+fn css_font<'a>(font: &'a str, fallback: &'a str) -> &'a str {
+    let valid = !font.is_empty()
+        && font.len() <= 200
+        && font.chars().all(|c| c.is_ascii_alphanumeric() || " ,-_'".contains(c));
+    if valid {
+        font
+    } else {
+        fallback
+    }
+}
+
+fn escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+        .replace('\'', "&#39;")
+}
+
+fn urlencode(value: &str) -> String {
+    value
+        .bytes()
+        .map(|byte| match byte {
+            b'A'..=b'Z' | b'a'..=b'z' | b'0'..=b'9' | b'-' | b'_' | b'.' | b'~' | b'/' => (byte as char).to_string(),
+            _ => format!("%{:02X}", byte),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn context() -> RenderContext {
+        RenderContext {
+            locale: "en".to_string(),
+            api_base: "/api/v1/auth".to_string(),
+            redirect_path: Some("/dashboard".to_string()),
+            nonce: "abc123".to_string(),
+            preview: false,
+        }
+    }
+
+    #[test]
+    fn test_validates_config() {
+        assert!(LoginPageConfig::default().validate().is_ok());
+
+        let mut config = LoginPageConfig::default();
+        config.blocks.retain(|block| *block != LoginBlock::AuthForm);
+        assert!(config.validate().is_err());
+
+        let mut config = LoginPageConfig::default();
+        config.legal_links.push(LegalLink {
+            label: LocalizedText::new("Terms"),
+            url: "javascript:alert(1)".to_string(),
+        });
+        assert!(config.validate().is_err());
+
+        let mut config = LoginPageConfig::default();
+        config.sso_buttons.push(SsoButton {
+            provider: "myspace".to_string(),
+            label: LocalizedText::new("Sign in with MySpace"),
+        });
+        assert!(config.validate().is_err());
+
+        let mut config = LoginPageConfig::default();
+        config.default_locale = "EN_us".to_string();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_resolves_locale() {
+        let config = LoginPageConfig {
+            default_locale: "en".to_string(),
+            locales: vec!["de".to_string(), "pt-br".to_string()],
+            ..LoginPageConfig::default()
+        };
+        assert_eq!(config.resolve_locale(["fr", "de-AT"]), "de");
+        assert_eq!(config.resolve_locale(["pt-BR"]), "pt-br");
+        assert_eq!(config.resolve_locale(["ja"]), "en");
+        assert_eq!(config.resolve_locale(Vec::<&str>::new()), "en");
+    }
+
+    #[test]
+    fn test_render_escapes_configuration() {
+        let mut page = HostedLoginPage::default();
+        page.branding.brand_name = "<script>alert(1)</script>".to_string();
+        page.branding.primary_color = "red;}body{display:none".to_string();
+        page.config.blocks.insert(
+            0,
+            LoginBlock::Heading {
+                text: LocalizedText {
+                    text: "Welcome".to_string(),
+                    translations: BTreeMap::from([("de".to_string(), "Willkommen \"zurück\"".to_string())]),
+                },
+            },
+        );
+
+        let html = render_page(&page, AuthPage::Login, &context());
+        assert!(!html.contains("<script>alert"));
+        assert!(html.contains("--primary:#2563eb"));
+        assert!(html.contains("<h1>Welcome</h1>"));
+        assert!(html.contains("<script nonce=\"abc123\">"));
+        assert!(html.contains("href=\"register?redirect=/dashboard\""));
+
+        let german = RenderContext {
+            locale: "de".to_string(),
+            ..context()
+        };
+        let html = render_page(&page, AuthPage::Login, &german);
+        assert!(html.contains("<h1>Willkommen &quot;zurück&quot;</h1>"));
+        assert!(html.contains("Anmelden"));
+    }
+
+    #[test]
+    fn test_preview_does_not_submit() {
+        let mut page = HostedLoginPage::default();
+        page.config.allow_registration = false;
+        let preview = RenderContext {
+            preview: true,
+            ..context()
+        };
+
+        let html = render_page(&page, AuthPage::Login, &preview);
+        assert!(html.contains("data-preview"));
+        assert!(!html.contains("<script"));
+        assert!(!html.contains("href=\"register"));
+    }
+
+    #[test]
+    fn test_safe_redirects() {
+        assert!(is_safe_redirect("/dashboard?tab=1"));
+        assert!(!is_safe_redirect("//evil.example.com"));
+        assert!(!is_safe_redirect("https://evil.example.com"));
+        assert!(!is_safe_redirect("/\\evil.example.com"));
+    }
+}
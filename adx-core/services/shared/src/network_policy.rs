@@ -0,0 +1,637 @@
+// Tenant network policies
+//
+// A tenant can restrict where its users connect from: an allowlist of CIDR
+// ranges, allowed or blocked countries, and a ban on TOR exit nodes and
+// known VPN ranges. Policies are kept in security-service, which also keeps
+// the anonymizer ranges, and enforced by api-gateway through
+// `NetworkPolicyClient`. Countries come from the edge in front of the
+// gateway; the platform does no geo lookups of its own.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+use std::collections::HashMap;
+use std::fmt;
+use std::net::IpAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+use crate::audit::{AuditCategory, AuditEvent, AuditOutcome};
+use crate::{Result, ServiceError};
+
+pub const DEFAULT_NETWORK_POLICY_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Audit action denied requests are recorded under
+pub const NETWORK_ACCESS_DENIED_ACTION: &str = "network_access_denied";
+
+/// An address range in CIDR notation; a bare address is a range of one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct IpCidr {
+    network: IpAddr,
+    prefix: u8,
+}
+
+impl IpCidr {
+    pub fn contains(&self, ip: IpAddr) -> bool {
+        match (self.network, ip.to_canonical()) {
+            (IpAddr::V4(network), IpAddr::V4(ip)) => {
+                let mask = u32::MAX.checked_shl(32 - self.prefix as u32).unwrap_or(0);
+                u32::from(network) & mask == u32::from(ip) & mask
+            }
+            (IpAddr::V6(network), IpAddr::V6(ip)) => {
+                let mask = u128::MAX.checked_shl(128 - self.prefix as u32).unwrap_or(0);
+                u128::from(network) & mask == u128::from(ip) & mask
+            }
+            _ => false,
+        }
+    }
+}
+
+impl FromStr for IpCidr {
+    type Err = ServiceError;
+
+    fn from_str(value: &str) -> Result<Self> {
+        let invalid = || ServiceError::Validation(format!("Invalid CIDR range: {}", value));
+        let (address, prefix) = match value.trim().split_once('/') {
+            Some((address, prefix)) => (address, Some(prefix)),
+            None => (value.trim(), None),
+        };
+
+        let network = IpAddr::from_str(address).map_err(|_| invalid())?.to_canonical();
+        let max_prefix = if network.is_ipv4() { 32 } else { 128 };
+        let prefix = match prefix {
+            Some(prefix) => prefix.parse::<u8>().map_err(|_| invalid())?,
+            None => max_prefix,
+        };
+        if prefix > max_prefix {
+            return Err(invalid());
+        }
+
+        // Keep only the network bits, so 10.1.2.3/8 is 10.0.0.0/8
+        let network = match network {
+            IpAddr::V4(address) => {
+                let mask = u32::MAX.checked_shl(32 - prefix as u32).unwrap_or(0);
+                IpAddr::V4((u32::from(address) & mask).into())
+            }
+            IpAddr::V6(address) => {
+                let mask = u128::MAX.checked_shl(128 - prefix as u32).unwrap_or(0);
+                IpAddr::V6((u128::from(address) & mask).into())
+            }
+        };
+
+        Ok(Self { network, prefix })
+    }
+}
+
+impl fmt::Display for IpCidr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}/{}", self.network, self.prefix)
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum AnonymizerCategory {
+    Tor,
+    Vpn,
+}
+
+impl AnonymizerCategory {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            AnonymizerCategory::Tor => "tor",
+            AnonymizerCategory::Vpn => "vpn",
+        }
+    }
+}
+
+/// A TOR exit node or VPN provider range
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AnonymizerRange {
+    pub cidr: String,
+    pub category: AnonymizerCategory,
+}
+
+/// A tenant's network restrictions. Every configured check must pass.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct NetworkPolicy {
+    pub tenant_id: String,
+    pub enabled: bool,
+    /// Ranges requests must come from; empty allows any address
+    #[serde(default)]
+    pub allowed_cidrs: Vec<String>,
+    /// ISO 3166-1 alpha-2 codes requests must come from; empty allows any
+    #[serde(default)]
+    pub allowed_countries: Vec<String>,
+    #[serde(default)]
+    pub blocked_countries: Vec<String>,
+    #[serde(default)]
+    pub block_tor: bool,
+    #[serde(default)]
+    pub block_vpn: bool,
+    pub updated_by: Option<String>,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+impl NetworkPolicy {
+    /// The policy of a tenant that has not configured one: no restrictions
+    pub fn unrestricted(tenant_id: &str) -> Self {
+        Self {
+            tenant_id: tenant_id.to_string(),
+            enabled: false,
+            allowed_cidrs: Vec::new(),
+            allowed_countries: Vec::new(),
+            blocked_countries: Vec::new(),
+            block_tor: false,
+            block_vpn: false,
+            updated_by: None,
+            updated_at: None,
+        }
+    }
+
+    /// Parse the ranges and normalize the country codes. Fails on a range or
+    /// country code that does not parse.
+    pub fn compile(&self) -> Result<CompiledNetworkPolicy> {
+        let allowed_cidrs = self
+            .allowed_cidrs
+            .iter()
+            .map(|cidr| cidr.parse())
+            .collect::<Result<Vec<IpCidr>>>()?;
+
+        Ok(CompiledNetworkPolicy {
+            enabled: self.enabled,
+            allowed_cidrs,
+            allowed_countries: normalize_countries(&self.allowed_countries)?,
+            blocked_countries: normalize_countries(&self.blocked_countries)?,
+            block_tor: self.block_tor,
+            block_vpn: self.block_vpn,
+        })
+    }
+}
+
+fn normalize_countries(countries: &[String]) -> Result<Vec<String>> {
+    countries
+        .iter()
+        .map(|country| {
+            let country = country.trim().to_ascii_uppercase();
+            if country.len() == 2 && country.chars().all(|c| c.is_ascii_alphabetic()) {
+                Ok(country)
+            } else {
+                Err(ServiceError::Validation(format!("Invalid country code: {}", country)))
+            }
+        })
+        .collect()
+}
+
+/// Parsed anonymizer ranges; ranges that do not parse are skipped
+#[derive(Debug, Clone, Default)]
+pub struct AnonymizerRanges {
+    ranges: Vec<(IpCidr, AnonymizerCategory)>,
+}
+
+impl AnonymizerRanges {
+    pub fn new(ranges: &[AnonymizerRange]) -> Self {
+        Self {
+            ranges: ranges
+                .iter()
+                .filter_map(|range| match range.cidr.parse() {
+                    Ok(cidr) => Some((cidr, range.category)),
+                    Err(e) => {
+                        tracing::warn!(cidr = %range.cidr, error = %e, "Skipping anonymizer range");
+                        None
+                    }
+                })
+                .collect(),
+        }
+    }
+
+    pub fn category_of(&self, ip: IpAddr) -> Option<AnonymizerCategory> {
+        self.ranges
+            .iter()
+            .find(|(cidr, _)| cidr.contains(ip))
+            .map(|(_, category)| *category)
+    }
+
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+}
+
+/// Why a request was refused
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum NetworkDenialReason {
+    UnknownAddress,
+    NotAllowlisted,
+    UnknownCountry,
+    CountryNotAllowed,
+    CountryBlocked,
+    Tor,
+    Vpn,
+}
+
+impl NetworkDenialReason {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            NetworkDenialReason::UnknownAddress => "unknown_address",
+            NetworkDenialReason::NotAllowlisted => "not_allowlisted",
+            NetworkDenialReason::UnknownCountry => "unknown_country",
+            NetworkDenialReason::CountryNotAllowed => "country_not_allowed",
+            NetworkDenialReason::CountryBlocked => "country_blocked",
+            NetworkDenialReason::Tor => "tor",
+            NetworkDenialReason::Vpn => "vpn",
+        }
+    }
+
+    /// Explanation safe to show the caller
+    pub fn message(&self) -> &'static str {
+        match self {
+            NetworkDenialReason::UnknownAddress => "The address of the request could not be determined",
+            NetworkDenialReason::NotAllowlisted => "Requests from this IP address are not allowed by the tenant's network policy",
+            NetworkDenialReason::UnknownCountry => "The tenant only allows requests from some countries and the country of this request is unknown",
+            NetworkDenialReason::CountryNotAllowed => "Requests from this country are not allowed by the tenant's network policy",
+            NetworkDenialReason::CountryBlocked => "Requests from this country are blocked by the tenant's network policy",
+            NetworkDenialReason::Tor => "Requests through TOR are blocked by the tenant's network policy",
+            NetworkDenialReason::Vpn => "Requests through VPNs are blocked by the tenant's network policy",
+        }
+    }
+
+    /// The audit event recording the refused request
+    pub fn audit_event(&self, tenant_id: &str, service: &str, ip: Option<IpAddr>, country: Option<&str>, path: &str) -> AuditEvent {
+        AuditEvent::new(tenant_id, service, AuditCategory::Security, NETWORK_ACCESS_DENIED_ACTION, "network_policy")
+            .outcome(AuditOutcome::Failure)
+            .client(ip.map(|ip| ip.to_string()), None)
+            .details(json!({
+                "reason": self,
+                "country": country,
+                "path": path,
+            }))
+    }
+}
+
+pub struct CompiledNetworkPolicy {
+    enabled: bool,
+    allowed_cidrs: Vec<IpCidr>,
+    allowed_countries: Vec<String>,
+    blocked_countries: Vec<String>,
+    block_tor: bool,
+    block_vpn: bool,
+}
+
+impl CompiledNetworkPolicy {
+    pub fn is_enforced(&self) -> bool {
+        self.enabled
+    }
+
+    pub fn blocks_anonymizers(&self) -> bool {
+        self.enabled && (self.block_tor || self.block_vpn)
+    }
+
+    /// Check a request from `ip`, located in `country` by the edge
+    pub fn evaluate(
+        &self,
+        ip: Option<IpAddr>,
+        country: Option<&str>,
+        anonymizers: &AnonymizerRanges,
+    ) -> std::result::Result<(), NetworkDenialReason> {
+        if !self.enabled {
+            return Ok(());
+        }
+
+        let restricts_address = !self.allowed_cidrs.is_empty() || self.block_tor || self.block_vpn;
+        let Some(ip) = ip else {
+            return if restricts_address {
+                Err(NetworkDenialReason::UnknownAddress)
+            } else {
+                self.check_country(country)
+            };
+        };
+
+        match anonymizers.category_of(ip) {
+            Some(AnonymizerCategory::Tor) if self.block_tor => return Err(NetworkDenialReason::Tor),
+            Some(AnonymizerCategory::Vpn) if self.block_vpn => return Err(NetworkDenialReason::Vpn),
+            _ => {}
+        }
+
+        if !self.allowed_cidrs.is_empty() && !self.allowed_cidrs.iter().any(|cidr| cidr.contains(ip)) {
+            return Err(NetworkDenialReason::NotAllowlisted);
+        }
+
+        self.check_country(country)
+    }
+
+    fn check_country(&self, country: Option<&str>) -> std::result::Result<(), NetworkDenialReason> {
+        let country = country.map(|country| country.trim().to_ascii_uppercase()).filter(|country| !country.is_empty());
+
+        if let Some(country) = &country {
+            if self.blocked_countries.contains(country) {
+                return Err(NetworkDenialReason::CountryBlocked);
+            }
+        }
+        if !self.allowed_countries.is_empty() {
+            match &country {
+                None => return Err(NetworkDenialReason::UnknownCountry),
+                Some(country) if !self.allowed_countries.contains(country) => {
+                    return Err(NetworkDenialReason::CountryNotAllowed)
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where network policies and anonymizer ranges come from
+#[async_trait]
+pub trait NetworkPolicySource: Send + Sync {
+    async fn fetch_policy(&self, tenant_id: &str) -> Result<NetworkPolicy>;
+
+    async fn fetch_anonymizer_ranges(&self) -> Result<Vec<AnonymizerRange>>;
+}
+
+/// Reads network policies from security-service over HTTP
+pub struct HttpNetworkPolicySource {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl HttpNetworkPolicySource {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    async fn get<T: serde::de::DeserializeOwned>(&self, path: &str, tenant_id: Option<&str>) -> Result<T> {
+        let mut request = self.client.get(format!("{}{}", self.base_url, path));
+        if let Some(tenant_id) = tenant_id {
+            request = request.header("X-Tenant-ID", tenant_id);
+        }
+
+        let response = request
+            .send()
+            .await
+            .map_err(|e| ServiceError::ExternalService(format!("Network policy request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(ServiceError::ExternalService(format!(
+                "Network policy request to {} returned {}",
+                path,
+                response.status()
+            )));
+        }
+
+        response
+            .json::<T>()
+            .await
+            .map_err(|e| ServiceError::ExternalService(format!("Invalid network policy response: {}", e)))
+    }
+}
+
+#[async_trait]
+impl NetworkPolicySource for HttpNetworkPolicySource {
+    async fn fetch_policy(&self, tenant_id: &str) -> Result<NetworkPolicy> {
+        self.get(&format!("/api/v1/network/tenants/{}/policy", tenant_id), Some(tenant_id))
+            .await
+    }
+
+    async fn fetch_anonymizer_ranges(&self) -> Result<Vec<AnonymizerRange>> {
+        self.get("/api/v1/network/anonymizer-ranges", None).await
+    }
+}
+
+struct Cached<T> {
+    value: Arc<T>,
+    fetched_at: Instant,
+}
+
+/// Cached, compiled network policies and anonymizer ranges
+pub struct NetworkPolicyClient {
+    source: Arc<dyn NetworkPolicySource>,
+    ttl: Duration,
+    policies: RwLock<HashMap<String, Cached<CompiledNetworkPolicy>>>,
+    anonymizers: RwLock<Option<Cached<AnonymizerRanges>>>,
+}
+
+impl NetworkPolicyClient {
+    pub fn new(source: Arc<dyn NetworkPolicySource>) -> Self {
+        Self {
+            source,
+            ttl: DEFAULT_NETWORK_POLICY_CACHE_TTL,
+            policies: RwLock::new(HashMap::new()),
+            anonymizers: RwLock::new(None),
+        }
+    }
+
+    /// Client backed by security-service at `base_url`
+    pub fn from_url(base_url: &str) -> Self {
+        Self::new(Arc::new(HttpNetworkPolicySource::new(base_url)))
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub async fn policy(&self, tenant_id: &str) -> Result<Arc<CompiledNetworkPolicy>> {
+        if let Some(cached) = self.policies.read().await.get(tenant_id) {
+            if cached.fetched_at.elapsed() < self.ttl {
+                return Ok(cached.value.clone());
+            }
+        }
+
+        match self.source.fetch_policy(tenant_id).await.and_then(|policy| policy.compile()) {
+            Ok(policy) => {
+                let policy = Arc::new(policy);
+                self.policies.write().await.insert(
+                    tenant_id.to_string(),
+                    Cached {
+                        value: policy.clone(),
+                        fetched_at: Instant::now(),
+                    },
+                );
+                Ok(policy)
+            }
+            Err(e) => {
+                // A policy once seen stays enforced while security-service is unreachable
+                if let Some(cached) = self.policies.read().await.get(tenant_id) {
+                    tracing::warn!(tenant_id = tenant_id, error = %e, "Serving stale network policy");
+                    return Ok(cached.value.clone());
+                }
+                Err(e)
+            }
+        }
+    }
+
+    pub async fn anonymizer_ranges(&self) -> Result<Arc<AnonymizerRanges>> {
+        if let Some(cached) = self.anonymizers.read().await.as_ref() {
+            if cached.fetched_at.elapsed() < self.ttl {
+                return Ok(cached.value.clone());
+            }
+        }
+
+        match self.source.fetch_anonymizer_ranges().await {
+            Ok(ranges) => {
+                let ranges = Arc::new(AnonymizerRanges::new(&ranges));
+                *self.anonymizers.write().await = Some(Cached {
+                    value: ranges.clone(),
+                    fetched_at: Instant::now(),
+                });
+                Ok(ranges)
+            }
+            Err(e) => {
+                if let Some(cached) = self.anonymizers.read().await.as_ref() {
+                    tracing::warn!(error = %e, "Serving stale anonymizer ranges");
+                    return Ok(cached.value.clone());
+                }
+                Err(e)
+            }
+        }
+    }
+
+    /// Check a request against the tenant's policy
+    pub async fn check(
+        &self,
+        tenant_id: &str,
+        ip: Option<IpAddr>,
+        country: Option<&str>,
+    ) -> Result<std::result::Result<(), NetworkDenialReason>> {
+        let policy = self.policy(tenant_id).await?;
+        let anonymizers = if policy.blocks_anonymizers() {
+            self.anonymizer_ranges().await?
+        } else {
+            Arc::new(AnonymizerRanges::default())
+        };
+        Ok(policy.evaluate(ip, country, &anonymizers))
+    }
+
+    pub async fn invalidate(&self, tenant_id: &str) {
+        self.policies.write().await.remove(tenant_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ip(value: &str) -> Option<IpAddr> {
+        Some(value.parse().unwrap())
+    }
+
+    #[test]
+    fn test_cidr_contains() {
+        let office: IpCidr = "203.0.113.0/24".parse().unwrap();
+        assert!(office.contains("203.0.113.77".parse().unwrap()));
+        assert!(office.contains("::ffff:203.0.113.5".parse().unwrap()));
+        assert!(!office.contains("203.0.114.1".parse().unwrap()));
+
+        assert_eq!("10.1.2.3/8".parse::<IpCidr>().unwrap().to_string(), "10.0.0.0/8");
+
+        let single: IpCidr = "198.51.100.7".parse().unwrap();
+        assert_eq!(single.to_string(), "198.51.100.7/32");
+        assert!(!single.contains("198.51.100.8".parse().unwrap()));
+
+        let v6: IpCidr = "2001:db8::/32".parse().unwrap();
+        assert!(v6.contains("2001:db8:1::1".parse().unwrap()));
+        assert!("0.0.0.0/0".parse::<IpCidr>().unwrap().contains("8.8.8.8".parse().unwrap()));
+
+        assert!("10.0.0.0/33".parse::<IpCidr>().is_err());
+        assert!("not-an-ip/8".parse::<IpCidr>().is_err());
+    }
+
+    #[test]
+    fn test_evaluate_policy() {
+        let policy = NetworkPolicy {
+            enabled: true,
+            allowed_cidrs: vec!["203.0.113.0/24".to_string(), "2001:db8::/32".to_string()],
+            blocked_countries: vec!["kp".to_string()],
+            block_tor: true,
+            ..NetworkPolicy::unrestricted("tenant-1")
+        }
+        .compile()
+        .unwrap();
+        let anonymizers = AnonymizerRanges::new(&[
+            AnonymizerRange {
+                cidr: "203.0.113.66/32".to_string(),
+                category: AnonymizerCategory::Tor,
+            },
+            AnonymizerRange {
+                cidr: "203.0.113.99/32".to_string(),
+                category: AnonymizerCategory::Vpn,
+            },
+        ]);
+
+        assert_eq!(policy.evaluate(ip("203.0.113.10"), Some("US"), &anonymizers), Ok(()));
+        assert_eq!(policy.evaluate(ip("2001:db8::5"), None, &anonymizers), Ok(()));
+        // VPNs are not blocked by this policy
+        assert_eq!(policy.evaluate(ip("203.0.113.99"), None, &anonymizers), Ok(()));
+        assert_eq!(
+            policy.evaluate(ip("198.51.100.1"), Some("US"), &anonymizers),
+            Err(NetworkDenialReason::NotAllowlisted)
+        );
+        assert_eq!(
+            policy.evaluate(ip("203.0.113.66"), Some("US"), &anonymizers),
+            Err(NetworkDenialReason::Tor)
+        );
+        assert_eq!(
+            policy.evaluate(ip("203.0.113.10"), Some("KP"), &anonymizers),
+            Err(NetworkDenialReason::CountryBlocked)
+        );
+        assert_eq!(policy.evaluate(None, Some("US"), &anonymizers), Err(NetworkDenialReason::UnknownAddress));
+    }
+
+    #[test]
+    fn test_allowed_countries_require_a_known_country() {
+        let policy = NetworkPolicy {
+            enabled: true,
+            allowed_countries: vec!["DE".to_string(), "fr".to_string()],
+            ..NetworkPolicy::unrestricted("tenant-1")
+        }
+        .compile()
+        .unwrap();
+        let anonymizers = AnonymizerRanges::default();
+
+        assert_eq!(policy.evaluate(ip("198.51.100.1"), Some("fr"), &anonymizers), Ok(()));
+        assert_eq!(
+            policy.evaluate(ip("198.51.100.1"), Some("US"), &anonymizers),
+            Err(NetworkDenialReason::CountryNotAllowed)
+        );
+        assert_eq!(
+            policy.evaluate(ip("198.51.100.1"), None, &anonymizers),
+            Err(NetworkDenialReason::UnknownCountry)
+        );
+        // Without address rules a missing address is no reason to refuse
+        assert_eq!(policy.evaluate(None, Some("DE"), &anonymizers), Ok(()));
+    }
+
+    #[test]
+    fn test_disabled_and_invalid_policies() {
+        let disabled = NetworkPolicy {
+            allowed_cidrs: vec!["10.0.0.0/8".to_string()],
+            ..NetworkPolicy::unrestricted("tenant-1")
+        }
+        .compile()
+        .unwrap();
+        assert_eq!(disabled.evaluate(ip("198.51.100.1"), None, &AnonymizerRanges::default()), Ok(()));
+
+        let invalid_range = NetworkPolicy {
+            allowed_cidrs: vec!["10.0.0.0/40".to_string()],
+            ..NetworkPolicy::unrestricted("tenant-1")
+        };
+        assert!(invalid_range.compile().is_err());
+
+        let invalid_country = NetworkPolicy {
+            blocked_countries: vec!["Germany".to_string()],
+            ..NetworkPolicy::unrestricted("tenant-1")
+        };
+        assert!(invalid_country.compile().is_err());
+    }
+}
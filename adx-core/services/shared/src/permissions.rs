@@ -0,0 +1,99 @@
+// Tenant permission client
+//
+// tenant-service decides what a user may do in a tenant from their
+// membership's role and direct grants. Services ask it through
+// `PermissionClient`, which uses tenant-service's internal gRPC port since
+// permission checks sit on the path of most requests.
+
+use adx_proto::tenant::v1::{self as proto, permission_service_client::PermissionServiceClient};
+use tonic::transport::Channel;
+
+use crate::telemetry::grpc_request;
+use crate::{Result, ServiceError};
+
+/// A user's role and direct grants within a tenant
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct TenantPermissions {
+    pub role: String,
+    pub permissions: Vec<String>,
+}
+
+pub struct PermissionClient {
+    client: PermissionServiceClient<Channel>,
+}
+
+impl PermissionClient {
+    /// Connects on first use, so services can start before tenant-service
+    pub fn new(endpoint: &str) -> Result<Self> {
+        let channel = Channel::from_shared(endpoint.to_string())
+            .map_err(|e| {
+                ServiceError::Configuration(format!("Invalid tenant-service gRPC endpoint {}: {}", endpoint, e))
+            })?
+            .connect_lazy();
+        Ok(Self {
+            client: PermissionServiceClient::new(channel),
+        })
+    }
+
+    /// Client for `TENANT_SERVICE_GRPC_URL`
+    pub fn from_env() -> Result<Self> {
+        Self::new(&std::env::var("TENANT_SERVICE_GRPC_URL").unwrap_or_else(|_| "http://localhost:50085".to_string()))
+    }
+
+    pub async fn has_permission(&self, tenant_id: &str, user_id: &str, permission: &str) -> Result<bool> {
+        let request = grpc_request(proto::CheckPermissionRequest {
+            tenant_id: tenant_id.to_string(),
+            user_id: user_id.to_string(),
+            permission: permission.to_string(),
+        });
+
+        let response = self
+            .client
+            .clone()
+            .check_permission(request)
+            .await
+            .map_err(|status| tenant_error(&format!("Checking {} for user {}", permission, user_id), status))?;
+        Ok(response.into_inner().allowed)
+    }
+
+    /// Fail with `Authorization` unless the user holds `permission` in the tenant
+    pub async fn require(&self, tenant_id: &str, user_id: &str, permission: &str) -> Result<()> {
+        if self.has_permission(tenant_id, user_id, permission).await? {
+            Ok(())
+        } else {
+            Err(ServiceError::Authorization(format!(
+                "User {} lacks {} in tenant {}",
+                user_id, permission, tenant_id
+            )))
+        }
+    }
+
+    /// None when the user isn't a member of the tenant
+    pub async fn permissions(&self, tenant_id: &str, user_id: &str) -> Result<Option<TenantPermissions>> {
+        let request = grpc_request(proto::ListPermissionsRequest {
+            tenant_id: tenant_id.to_string(),
+            user_id: user_id.to_string(),
+        });
+
+        match self.client.clone().list_permissions(request).await {
+            Ok(response) => {
+                let response = response.into_inner();
+                Ok(Some(TenantPermissions {
+                    role: response.role,
+                    permissions: response.permissions,
+                }))
+            }
+            Err(status) if status.code() == tonic::Code::NotFound => Ok(None),
+            Err(status) => Err(tenant_error(&format!("Listing permissions of user {}", user_id), status)),
+        }
+    }
+}
+
+fn tenant_error(operation: &str, status: tonic::Status) -> ServiceError {
+    ServiceError::ExternalService(format!(
+        "{} failed: {:?} {}",
+        operation,
+        status.code(),
+        status.message()
+    ))
+}
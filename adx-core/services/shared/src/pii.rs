@@ -0,0 +1,86 @@
+// PII detectors
+//
+// The card number and SSN detectors shared by DLP scanning and AI
+// moderation, so a value one of them flags the other flags too. A pattern
+// match is only a candidate: check it with `luhn_valid` or `ssn_valid`
+// before treating it as a hit.
+
+use regex::Regex;
+use std::sync::OnceLock;
+
+/// 13 to 19 digits, optionally grouped by spaces or dashes
+pub fn credit_card_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\b(?:\d[ -]?){12,18}\d\b").expect("card pattern is valid"))
+}
+
+/// US social security numbers in the 123-45-6789 form
+pub fn ssn_pattern() -> &'static Regex {
+    static PATTERN: OnceLock<Regex> = OnceLock::new();
+    PATTERN.get_or_init(|| Regex::new(r"\b\d{3}-\d{2}-\d{4}\b").expect("SSN pattern is valid"))
+}
+
+/// Card numbers carry a Luhn check digit, which rules out most other long
+/// digit sequences
+pub fn luhn_valid(number: &str) -> bool {
+    let digits: Vec<u32> = number.chars().filter_map(|c| c.to_digit(10)).collect();
+    let sum: u32 = digits
+        .iter()
+        .rev()
+        .enumerate()
+        .map(|(i, &digit)| {
+            if i % 2 == 1 {
+                let doubled = digit * 2;
+                if doubled > 9 {
+                    doubled - 9
+                } else {
+                    doubled
+                }
+            } else {
+                digit
+            }
+        })
+        .sum();
+    sum.is_multiple_of(10)
+}
+
+/// Area 000, 666 and 900-999, group 00 and serial 0000 are never issued
+pub fn ssn_valid(ssn: &str) -> bool {
+    let parts: Vec<&str> = ssn.split('-').collect();
+    let [area, group, serial] = parts.as_slice() else {
+        return false;
+    };
+    *area != "000" && *area != "666" && !area.starts_with('9') && *group != "00" && *serial != "0000"
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn card_hits(text: &str) -> Vec<&str> {
+        credit_card_pattern()
+            .find_iter(text)
+            .map(|found| found.as_str())
+            .filter(|found| luhn_valid(found))
+            .collect()
+    }
+
+    #[test]
+    fn test_card_numbers_need_a_luhn_check_digit() {
+        assert_eq!(
+            card_hits("Pay with 4111 1111 1111 1111 or 5500-0000-0000-0004, not 1234 5678 9012 3456."),
+            vec!["4111 1111 1111 1111", "5500-0000-0000-0004"]
+        );
+        assert!(card_hits("Order 123456789012 shipped").is_empty());
+    }
+
+    #[test]
+    fn test_ssn_rejects_never_issued_numbers() {
+        let hits: Vec<&str> = ssn_pattern()
+            .find_iter("123-45-6789, 000-12-3456, 666-12-3456, 912-34-5678, 123-00-4567, 123-45-0000")
+            .map(|found| found.as_str())
+            .filter(|found| ssn_valid(found))
+            .collect();
+        assert_eq!(hits, vec!["123-45-6789"]);
+    }
+}
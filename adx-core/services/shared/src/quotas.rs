@@ -0,0 +1,551 @@
+// License quota client
+//
+// Quotas and seats are tracked by license-service. Services use `QuotaClient`
+// to check a tenant's quota before creating what it counts, to count usage as
+// it happens, and to assign and free the seats users hold.
+//
+// `consume` checks and counts in one call against license-service's Redis
+// counters, so it is cheap enough for every request. Counters reset with the
+// quota's period (hourly, daily, monthly); allocation quotas that never reset
+// are given back with `release`.
+//
+// Services reach license-service over its internal gRPC port when
+// `LICENSE_SERVICE_GRPC_URL` is set, and over REST otherwise.
+
+use adx_proto::quota::v1::{self as proto, quota_service_client::QuotaServiceClient};
+use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tonic::transport::Channel;
+
+use crate::telemetry::grpc_request;
+use crate::{Result, ServiceError};
+
+/// The quota whose seats are assigned to users
+pub const SEAT_QUOTA: &str = "users_per_tenant";
+
+/// Largest single upload in megabytes, checked per file
+pub const FILE_UPLOAD_SIZE_QUOTA: &str = "file_upload_size_mb";
+
+/// Workflow starts per hour
+pub const WORKFLOW_EXECUTIONS_QUOTA: &str = "workflow_executions_per_hour";
+
+/// AI requests per day
+pub const AI_REQUESTS_QUOTA: &str = "ai_requests_per_day";
+
+/// License-service's answer to a quota check
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct QuotaCheck {
+    pub quota_name: String,
+    pub allowed: bool,
+    pub current_usage: i64,
+    /// -1 means unlimited
+    pub quota_limit: i64,
+    /// -1 means unlimited
+    pub remaining: i64,
+}
+
+/// Where quota checks and seat changes go
+#[async_trait]
+pub trait QuotaSource: Send + Sync {
+    async fn check_quota(&self, tenant_id: &str, quota_name: &str, amount: i64) -> Result<QuotaCheck>;
+
+    /// Count `amount` unless that goes over the limit; `allowed` tells which
+    async fn consume_quota(&self, tenant_id: &str, quota_name: &str, amount: i64) -> Result<QuotaCheck>;
+
+    async fn release_quota(&self, tenant_id: &str, quota_name: &str, amount: i64) -> Result<()>;
+
+    async fn assign_seat(&self, tenant_id: &str, user_id: &str, assigned_by: Option<&str>) -> Result<()>;
+
+    async fn unassign_seat(&self, tenant_id: &str, user_id: &str) -> Result<()>;
+}
+
+#[derive(Deserialize)]
+struct LicenseResponse<T> {
+    data: Option<T>,
+}
+
+/// Talks to license-service over HTTP
+pub struct HttpQuotaSource {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl HttpQuotaSource {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    async fn post(&self, path: &str, body: serde_json::Value) -> Result<reqwest::Response> {
+        self.client
+            .post(format!("{}{}", self.base_url, path))
+            .json(&body)
+            .send()
+            .await
+            .map_err(|e| ServiceError::ExternalService(format!("License service request failed: {}", e)))
+    }
+}
+
+#[async_trait]
+impl QuotaSource for HttpQuotaSource {
+    async fn check_quota(&self, tenant_id: &str, quota_name: &str, amount: i64) -> Result<QuotaCheck> {
+        let response = self
+            .post(
+                "/quotas/check",
+                serde_json::json!({
+                    "tenant_id": tenant_id,
+                    "quota_name": quota_name,
+                    "requested_amount": amount,
+                }),
+            )
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(ServiceError::ExternalService(format!(
+                "Quota check of {} for tenant {} returned {}",
+                quota_name,
+                tenant_id,
+                response.status()
+            )));
+        }
+
+        quota_check(response).await
+    }
+
+    async fn consume_quota(&self, tenant_id: &str, quota_name: &str, amount: i64) -> Result<QuotaCheck> {
+        let response = self
+            .post(
+                "/quotas/consume",
+                serde_json::json!({
+                    "tenant_id": tenant_id,
+                    "quota_name": quota_name,
+                    "amount": amount,
+                }),
+            )
+            .await?;
+
+        if !response.status().is_success() {
+            return Err(ServiceError::ExternalService(format!(
+                "Consuming {} for tenant {} returned {}",
+                quota_name,
+                tenant_id,
+                response.status()
+            )));
+        }
+
+        quota_check(response).await
+    }
+
+    async fn release_quota(&self, tenant_id: &str, quota_name: &str, amount: i64) -> Result<()> {
+        let response = self
+            .post(
+                "/quotas/release",
+                serde_json::json!({
+                    "tenant_id": tenant_id,
+                    "quota_name": quota_name,
+                    "amount": amount,
+                }),
+            )
+            .await?;
+
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            status => Err(ServiceError::ExternalService(format!(
+                "Releasing {} for tenant {} returned {}",
+                quota_name, tenant_id, status
+            ))),
+        }
+    }
+
+    async fn assign_seat(&self, tenant_id: &str, user_id: &str, assigned_by: Option<&str>) -> Result<()> {
+        let response = self
+            .post(
+                "/seats/assign",
+                serde_json::json!({
+                    "tenant_id": tenant_id,
+                    "user_id": user_id,
+                    "assigned_by": assigned_by,
+                }),
+            )
+            .await?;
+
+        match response.status() {
+            status if status.is_success() => Ok(()),
+            reqwest::StatusCode::TOO_MANY_REQUESTS => Err(ServiceError::QuotaExceeded(format!(
+                "Tenant {} has no seats left",
+                tenant_id
+            ))),
+            status => Err(ServiceError::ExternalService(format!(
+                "Seat assignment for user {} returned {}",
+                user_id, status
+            ))),
+        }
+    }
+
+    async fn unassign_seat(&self, tenant_id: &str, user_id: &str) -> Result<()> {
+        let response = self
+            .post(
+                "/seats/unassign",
+                serde_json::json!({
+                    "tenant_id": tenant_id,
+                    "user_id": user_id,
+                }),
+            )
+            .await?;
+
+        // A user without a seat has nothing to free
+        match response.status() {
+            status if status.is_success() || status == reqwest::StatusCode::NOT_FOUND => Ok(()),
+            status => Err(ServiceError::ExternalService(format!(
+                "Freeing the seat of user {} returned {}",
+                user_id, status
+            ))),
+        }
+    }
+}
+
+async fn quota_check(response: reqwest::Response) -> Result<QuotaCheck> {
+    response
+        .json::<LicenseResponse<QuotaCheck>>()
+        .await
+        .map_err(|e| ServiceError::ExternalService(format!("Invalid quota check response: {}", e)))?
+        .data
+        .ok_or_else(|| ServiceError::ExternalService("Quota check response has no data".to_string()))
+}
+
+/// Talks to license-service's internal gRPC port
+pub struct GrpcQuotaSource {
+    client: QuotaServiceClient<Channel>,
+}
+
+impl GrpcQuotaSource {
+    /// Connects on first use, so services can start before license-service
+    pub fn new(endpoint: &str) -> Result<Self> {
+        let channel = Channel::from_shared(endpoint.to_string())
+            .map_err(|e| {
+                ServiceError::Configuration(format!("Invalid license-service gRPC endpoint {}: {}", endpoint, e))
+            })?
+            .connect_lazy();
+        Ok(Self {
+            client: QuotaServiceClient::new(channel),
+        })
+    }
+
+    fn quota_request(tenant_id: &str, quota_name: &str, amount: i64) -> tonic::Request<proto::QuotaRequest> {
+        grpc_request(proto::QuotaRequest {
+            tenant_id: tenant_id.to_string(),
+            quota_name: quota_name.to_string(),
+            amount,
+        })
+    }
+}
+
+#[async_trait]
+impl QuotaSource for GrpcQuotaSource {
+    async fn check_quota(&self, tenant_id: &str, quota_name: &str, amount: i64) -> Result<QuotaCheck> {
+        let response = self
+            .client
+            .clone()
+            .check_quota(Self::quota_request(tenant_id, quota_name, amount))
+            .await
+            .map_err(|status| license_error(&format!("Quota check of {} for tenant {}", quota_name, tenant_id), status))?;
+        Ok(response.into_inner().into())
+    }
+
+    async fn consume_quota(&self, tenant_id: &str, quota_name: &str, amount: i64) -> Result<QuotaCheck> {
+        let response = self
+            .client
+            .clone()
+            .consume_quota(Self::quota_request(tenant_id, quota_name, amount))
+            .await
+            .map_err(|status| license_error(&format!("Consuming {} for tenant {}", quota_name, tenant_id), status))?;
+        Ok(response.into_inner().into())
+    }
+
+    async fn release_quota(&self, tenant_id: &str, quota_name: &str, amount: i64) -> Result<()> {
+        self.client
+            .clone()
+            .release_quota(Self::quota_request(tenant_id, quota_name, amount))
+            .await
+            .map_err(|status| license_error(&format!("Releasing {} for tenant {}", quota_name, tenant_id), status))?;
+        Ok(())
+    }
+
+    async fn assign_seat(&self, tenant_id: &str, user_id: &str, assigned_by: Option<&str>) -> Result<()> {
+        let request = grpc_request(proto::AssignSeatRequest {
+            tenant_id: tenant_id.to_string(),
+            user_id: user_id.to_string(),
+            assigned_by: assigned_by.map(str::to_string),
+        });
+
+        match self.client.clone().assign_seat(request).await {
+            Ok(_) => Ok(()),
+            Err(status) if status.code() == tonic::Code::ResourceExhausted => Err(ServiceError::QuotaExceeded(
+                format!("Tenant {} has no seats left", tenant_id),
+            )),
+            Err(status) => Err(license_error(&format!("Seat assignment for user {}", user_id), status)),
+        }
+    }
+
+    async fn unassign_seat(&self, tenant_id: &str, user_id: &str) -> Result<()> {
+        let request = grpc_request(proto::UnassignSeatRequest {
+            tenant_id: tenant_id.to_string(),
+            user_id: user_id.to_string(),
+        });
+
+        // A user without a seat has nothing to free
+        self.client
+            .clone()
+            .unassign_seat(request)
+            .await
+            .map_err(|status| license_error(&format!("Freeing the seat of user {}", user_id), status))?;
+        Ok(())
+    }
+}
+
+impl From<proto::QuotaCheck> for QuotaCheck {
+    fn from(check: proto::QuotaCheck) -> Self {
+        Self {
+            quota_name: check.quota_name,
+            allowed: check.allowed,
+            current_usage: check.current_usage,
+            quota_limit: check.quota_limit,
+            remaining: check.remaining,
+        }
+    }
+}
+
+fn license_error(operation: &str, status: tonic::Status) -> ServiceError {
+    ServiceError::ExternalService(format!(
+        "{} failed: {:?} {}",
+        operation,
+        status.code(),
+        status.message()
+    ))
+}
+
+/// Quota checks, usage counting and seat changes for one service
+pub struct QuotaClient {
+    source: Arc<dyn QuotaSource>,
+}
+
+impl QuotaClient {
+    pub fn new(source: Arc<dyn QuotaSource>) -> Self {
+        Self { source }
+    }
+
+    /// Client backed by license-service at `base_url`
+    pub fn from_url(base_url: &str) -> Self {
+        Self::new(Arc::new(HttpQuotaSource::new(base_url)))
+    }
+
+    /// Client backed by license-service's gRPC port at `endpoint`
+    pub fn from_grpc(endpoint: &str) -> Result<Self> {
+        Ok(Self::new(Arc::new(GrpcQuotaSource::new(endpoint)?)))
+    }
+
+    /// gRPC when `LICENSE_SERVICE_GRPC_URL` is set, REST at
+    /// `LICENSE_SERVICE_URL` otherwise
+    pub fn from_env() -> Result<Self> {
+        match std::env::var("LICENSE_SERVICE_GRPC_URL") {
+            Ok(endpoint) if !endpoint.is_empty() => Self::from_grpc(&endpoint),
+            _ => Ok(Self::from_url(
+                &std::env::var("LICENSE_SERVICE_URL").unwrap_or_else(|_| "http://localhost:8087".to_string()),
+            )),
+        }
+    }
+
+    pub async fn check(&self, tenant_id: &str, quota_name: &str, amount: i64) -> Result<QuotaCheck> {
+        self.source.check_quota(tenant_id, quota_name, amount).await
+    }
+
+    /// Fail with `QuotaExceeded` unless the quota has room for `amount` more
+    pub async fn require(&self, tenant_id: &str, quota_name: &str, amount: i64) -> Result<QuotaCheck> {
+        let check = self.check(tenant_id, quota_name, amount).await?;
+        if !check.allowed {
+            return Err(quota_exceeded(tenant_id, &check));
+        }
+        Ok(check)
+    }
+
+    /// Count `amount` against the quota, failing with `QuotaExceeded` and
+    /// counting nothing when it has no room left
+    pub async fn consume(&self, tenant_id: &str, quota_name: &str, amount: i64) -> Result<QuotaCheck> {
+        let check = self.source.consume_quota(tenant_id, quota_name, amount).await?;
+        if !check.allowed {
+            return Err(quota_exceeded(tenant_id, &check));
+        }
+        Ok(check)
+    }
+
+    /// Give back usage of an allocation quota
+    pub async fn release(&self, tenant_id: &str, quota_name: &str, amount: i64) -> Result<()> {
+        self.source.release_quota(tenant_id, quota_name, amount).await
+    }
+
+    /// Fail with `QuotaExceeded` when the tenant has no seat for another user
+    pub async fn require_seat(&self, tenant_id: &str) -> Result<QuotaCheck> {
+        self.require(tenant_id, SEAT_QUOTA, 1).await
+    }
+
+    pub async fn assign_seat(&self, tenant_id: &str, user_id: &str, assigned_by: Option<&str>) -> Result<()> {
+        self.source.assign_seat(tenant_id, user_id, assigned_by).await
+    }
+
+    pub async fn unassign_seat(&self, tenant_id: &str, user_id: &str) -> Result<()> {
+        self.source.unassign_seat(tenant_id, user_id).await
+    }
+}
+
+fn quota_exceeded(tenant_id: &str, check: &QuotaCheck) -> ServiceError {
+    ServiceError::QuotaExceeded(format!(
+        "{} of tenant {} is used up ({} of {})",
+        check.quota_name, tenant_id, check.current_usage, check.quota_limit
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::{HashMap, HashSet};
+    use tokio::sync::Mutex;
+
+    struct SeatSource {
+        limit: i64,
+        seats: Mutex<HashSet<String>>,
+        usage: Mutex<HashMap<String, i64>>,
+    }
+
+    impl SeatSource {
+        fn counted(&self, quota_name: &str, used: i64, allowed: bool) -> QuotaCheck {
+            QuotaCheck {
+                quota_name: quota_name.to_string(),
+                allowed,
+                current_usage: used,
+                quota_limit: self.limit,
+                remaining: (self.limit - used).max(0),
+            }
+        }
+    }
+
+    #[async_trait]
+    impl QuotaSource for SeatSource {
+        async fn check_quota(&self, _tenant_id: &str, quota_name: &str, amount: i64) -> Result<QuotaCheck> {
+            let used = self.seats.lock().await.len() as i64;
+            Ok(QuotaCheck {
+                quota_name: quota_name.to_string(),
+                allowed: used + amount <= self.limit,
+                current_usage: used,
+                quota_limit: self.limit,
+                remaining: (self.limit - used).max(0),
+            })
+        }
+
+        async fn consume_quota(&self, _tenant_id: &str, quota_name: &str, amount: i64) -> Result<QuotaCheck> {
+            let mut usage = self.usage.lock().await;
+            let used = usage.entry(quota_name.to_string()).or_insert(0);
+            if *used + amount > self.limit {
+                return Ok(self.counted(quota_name, *used, false));
+            }
+            *used += amount;
+            Ok(self.counted(quota_name, *used, true))
+        }
+
+        async fn release_quota(&self, _tenant_id: &str, quota_name: &str, amount: i64) -> Result<()> {
+            let mut usage = self.usage.lock().await;
+            let used = usage.entry(quota_name.to_string()).or_insert(0);
+            *used = (*used - amount).max(0);
+            Ok(())
+        }
+
+        async fn assign_seat(&self, tenant_id: &str, user_id: &str, _assigned_by: Option<&str>) -> Result<()> {
+            let mut seats = self.seats.lock().await;
+            if !seats.contains(user_id) && seats.len() as i64 >= self.limit {
+                return Err(ServiceError::QuotaExceeded(format!("Tenant {} has no seats left", tenant_id)));
+            }
+            seats.insert(user_id.to_string());
+            Ok(())
+        }
+
+        async fn unassign_seat(&self, _tenant_id: &str, user_id: &str) -> Result<()> {
+            self.seats.lock().await.remove(user_id);
+            Ok(())
+        }
+    }
+
+    fn client(limit: i64) -> QuotaClient {
+        QuotaClient::new(Arc::new(SeatSource {
+            limit,
+            seats: Mutex::new(HashSet::new()),
+            usage: Mutex::new(HashMap::new()),
+        }))
+    }
+
+    #[tokio::test]
+    async fn test_require_seat_fails_once_seats_are_used_up() {
+        let client = client(1);
+
+        client.require_seat("tenant-1").await.unwrap();
+        client.assign_seat("tenant-1", "user-1", None).await.unwrap();
+
+        let err = client.require_seat("tenant-1").await.unwrap_err();
+        assert!(matches!(err, ServiceError::QuotaExceeded(_)));
+        assert_eq!(err.status_code(), 429);
+    }
+
+    #[tokio::test]
+    async fn test_freed_seat_can_be_reused() {
+        let client = client(1);
+
+        client.assign_seat("tenant-1", "user-1", None).await.unwrap();
+        client.unassign_seat("tenant-1", "user-1").await.unwrap();
+
+        let check = client.require_seat("tenant-1").await.unwrap();
+        assert_eq!(check.current_usage, 0);
+        assert_eq!(check.remaining, 1);
+    }
+
+    #[tokio::test]
+    async fn test_consume_counts_until_the_limit_and_then_fails() {
+        let client = client(3);
+
+        client.consume("tenant-1", WORKFLOW_EXECUTIONS_QUOTA, 2).await.unwrap();
+        let err = client.consume("tenant-1", WORKFLOW_EXECUTIONS_QUOTA, 2).await.unwrap_err();
+        assert!(matches!(err, ServiceError::QuotaExceeded(_)));
+
+        // The refused amount was not counted
+        let check = client.consume("tenant-1", WORKFLOW_EXECUTIONS_QUOTA, 1).await.unwrap();
+        assert_eq!(check.current_usage, 3);
+        assert_eq!(check.remaining, 0);
+    }
+
+    #[test]
+    fn test_grpc_quota_check_conversion() {
+        let check: QuotaCheck = proto::QuotaCheck {
+            quota_name: AI_REQUESTS_QUOTA.to_string(),
+            allowed: false,
+            current_usage: 100,
+            quota_limit: 100,
+            remaining: 0,
+        }
+        .into();
+
+        assert_eq!(check.quota_name, AI_REQUESTS_QUOTA);
+        assert!(!check.allowed);
+        assert_eq!(check.remaining, 0);
+    }
+
+    #[tokio::test]
+    async fn test_released_usage_can_be_consumed_again() {
+        let client = client(2);
+
+        client.consume("tenant-1", AI_REQUESTS_QUOTA, 2).await.unwrap();
+        client.release("tenant-1", AI_REQUESTS_QUOTA, 1).await.unwrap();
+
+        let check = client.consume("tenant-1", AI_REQUESTS_QUOTA, 1).await.unwrap();
+        assert_eq!(check.current_usage, 2);
+    }
+}
@@ -0,0 +1,328 @@
+// Signing key sets
+//
+// Security-service rotates the platform's JWT signing keys and webhook
+// signing secrets. While a rotation is under way several keys are valid at
+// once: the staged key about to take over, the active key, and the key it
+// replaced until its overlap window closes. Validators fetch the set through
+// `SigningKeyClient` and check a token against the key named by its `kid`.
+
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+use tokio::sync::RwLock;
+
+use crate::{Result, ServiceError};
+
+pub const DEFAULT_SIGNING_KEY_CACHE_TTL: Duration = Duration::from_secs(60);
+
+/// Shortest gap between refetches triggered by a `kid` the cache has not seen,
+/// so forged key ids cannot hammer security-service
+const UNKNOWN_KID_REFRESH_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Owner of platform-wide keys such as the JWT signing keys
+pub const PLATFORM_KEY_OWNER: &str = "platform";
+pub const JWT_SIGNING_PURPOSE: &str = "jwt_signing";
+pub const WEBHOOK_SIGNING_PURPOSE: &str = "webhook_signing";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum SigningKeyStatus {
+    /// Staged; accepted by validators but not yet used to sign
+    Pending,
+    Active,
+    /// Replaced; accepted until `expires_at`
+    Retiring,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SigningKey {
+    pub kid: String,
+    pub status: SigningKeyStatus,
+    pub secret: String,
+    pub activated_at: Option<DateTime<Utc>>,
+    pub expires_at: Option<DateTime<Utc>>,
+}
+
+impl SigningKey {
+    pub fn is_valid_at(&self, now: DateTime<Utc>) -> bool {
+        self.expires_at.map_or(true, |expires_at| now < expires_at)
+    }
+}
+
+/// Every key currently valid for one purpose and owner
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct SigningKeySet {
+    pub purpose: String,
+    pub owner_id: String,
+    pub keys: Vec<SigningKey>,
+    pub generated_at: DateTime<Utc>,
+}
+
+impl SigningKeySet {
+    /// The key new signatures are made with
+    pub fn active(&self) -> Option<&SigningKey> {
+        self.keys.iter().find(|key| key.status == SigningKeyStatus::Active)
+    }
+
+    pub fn contains(&self, kid: &str) -> bool {
+        self.keys.iter().any(|key| key.kid == kid)
+    }
+
+    /// Secrets a signature may have been made with. A `kid` narrows it to
+    /// that key; without one every valid key is tried, the active one first.
+    pub fn verification_secrets(&self, kid: Option<&str>, now: DateTime<Utc>) -> Vec<&str> {
+        let mut keys: Vec<&SigningKey> = self
+            .keys
+            .iter()
+            .filter(|key| key.is_valid_at(now))
+            .filter(|key| kid.map_or(true, |kid| key.kid == kid))
+            .collect();
+        keys.sort_by_key(|key| match key.status {
+            SigningKeyStatus::Active => 0,
+            SigningKeyStatus::Retiring => 1,
+            SigningKeyStatus::Pending => 2,
+        });
+        keys.into_iter().map(|key| key.secret.as_str()).collect()
+    }
+}
+
+/// Where key sets come from
+#[async_trait]
+pub trait SigningKeySource: Send + Sync {
+    async fn fetch_key_set(&self, purpose: &str, owner_id: &str) -> Result<SigningKeySet>;
+}
+
+/// Reads key sets from security-service over HTTP
+pub struct HttpSigningKeySource {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl HttpSigningKeySource {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+}
+
+#[async_trait]
+impl SigningKeySource for HttpSigningKeySource {
+    async fn fetch_key_set(&self, purpose: &str, owner_id: &str) -> Result<SigningKeySet> {
+        let url = format!("{}/api/v1/keys/{}/{}/keyset", self.base_url, purpose, owner_id);
+
+        let response = self
+            .client
+            .get(&url)
+            .send()
+            .await
+            .map_err(|e| ServiceError::ExternalService(format!("Key set request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            return Err(ServiceError::ExternalService(format!(
+                "Key set request for {}/{} returned {}",
+                purpose,
+                owner_id,
+                response.status()
+            )));
+        }
+
+        response
+            .json::<SigningKeySet>()
+            .await
+            .map_err(|e| ServiceError::ExternalService(format!("Invalid key set response: {}", e)))
+    }
+}
+
+struct CachedKeySet {
+    key_set: SigningKeySet,
+    fetched_at: Instant,
+}
+
+/// Cached key set lookups
+pub struct SigningKeyClient {
+    source: Arc<dyn SigningKeySource>,
+    ttl: Duration,
+    cache: RwLock<HashMap<String, CachedKeySet>>,
+}
+
+impl SigningKeyClient {
+    pub fn new(source: Arc<dyn SigningKeySource>) -> Self {
+        Self {
+            source,
+            ttl: DEFAULT_SIGNING_KEY_CACHE_TTL,
+            cache: RwLock::new(HashMap::new()),
+        }
+    }
+
+    /// Client backed by security-service at `base_url`
+    pub fn from_url(base_url: &str) -> Self {
+        Self::new(Arc::new(HttpSigningKeySource::new(base_url)))
+    }
+
+    pub fn with_ttl(mut self, ttl: Duration) -> Self {
+        self.ttl = ttl;
+        self
+    }
+
+    pub async fn key_set(&self, purpose: &str, owner_id: &str) -> Result<SigningKeySet> {
+        let cache_key = format!("{}/{}", purpose, owner_id);
+        if let Some(cached) = self.cache.read().await.get(&cache_key) {
+            if cached.fetched_at.elapsed() < self.ttl {
+                return Ok(cached.key_set.clone());
+            }
+        }
+        self.refresh(purpose, owner_id).await
+    }
+
+    /// Key set able to check a signature with the given `kid`. A key id the
+    /// cached set does not know may belong to a key activated since it was
+    /// fetched, so the set is refetched before giving up on it.
+    pub async fn key_set_for(&self, purpose: &str, owner_id: &str, kid: Option<&str>) -> Result<SigningKeySet> {
+        let key_set = self.key_set(purpose, owner_id).await?;
+        let Some(kid) = kid else {
+            return Ok(key_set);
+        };
+        if key_set.contains(kid) {
+            return Ok(key_set);
+        }
+
+        let cache_key = format!("{}/{}", purpose, owner_id);
+        let recently_fetched = self
+            .cache
+            .read()
+            .await
+            .get(&cache_key)
+            .map_or(false, |cached| cached.fetched_at.elapsed() < UNKNOWN_KID_REFRESH_INTERVAL);
+        if recently_fetched {
+            return Ok(key_set);
+        }
+        self.refresh(purpose, owner_id).await
+    }
+
+    /// The platform's JWT signing keys
+    pub async fn jwt_key_set(&self, kid: Option<&str>) -> Result<SigningKeySet> {
+        self.key_set_for(JWT_SIGNING_PURPOSE, PLATFORM_KEY_OWNER, kid).await
+    }
+
+    async fn refresh(&self, purpose: &str, owner_id: &str) -> Result<SigningKeySet> {
+        let cache_key = format!("{}/{}", purpose, owner_id);
+
+        match self.source.fetch_key_set(purpose, owner_id).await {
+            Ok(key_set) => {
+                self.cache.write().await.insert(
+                    cache_key,
+                    CachedKeySet {
+                        key_set: key_set.clone(),
+                        fetched_at: Instant::now(),
+                    },
+                );
+                Ok(key_set)
+            }
+            Err(e) => {
+                // Keep validating with the last keys rather than rejecting everything while security-service is unreachable
+                if let Some(cached) = self.cache.read().await.get(&cache_key) {
+                    tracing::warn!(purpose = purpose, owner_id = owner_id, error = %e, "Serving stale signing keys");
+                    return Ok(cached.key_set.clone());
+                }
+                Err(e)
+            }
+        }
+    }
+
+    pub async fn invalidate_all(&self) {
+        self.cache.write().await.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    fn key(kid: &str, status: SigningKeyStatus, expires_at: Option<DateTime<Utc>>) -> SigningKey {
+        SigningKey {
+            kid: kid.to_string(),
+            status,
+            secret: format!("secret-{}", kid),
+            activated_at: None,
+            expires_at,
+        }
+    }
+
+    /// Serves a set that gains a new active key from the second fetch on
+    struct RotatingSource {
+        calls: AtomicUsize,
+    }
+
+    #[async_trait]
+    impl SigningKeySource for RotatingSource {
+        async fn fetch_key_set(&self, purpose: &str, owner_id: &str) -> Result<SigningKeySet> {
+            let call = self.calls.fetch_add(1, Ordering::SeqCst);
+            let keys = if call == 0 {
+                vec![key("k1", SigningKeyStatus::Active, None)]
+            } else {
+                vec![
+                    key("k2", SigningKeyStatus::Active, None),
+                    key("k1", SigningKeyStatus::Retiring, None),
+                ]
+            };
+
+            Ok(SigningKeySet {
+                purpose: purpose.to_string(),
+                owner_id: owner_id.to_string(),
+                keys,
+                generated_at: Utc::now(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_verification_secrets_follow_kid_and_expiry() {
+        let now = Utc::now();
+        let key_set = SigningKeySet {
+            purpose: JWT_SIGNING_PURPOSE.to_string(),
+            owner_id: PLATFORM_KEY_OWNER.to_string(),
+            keys: vec![
+                key("old", SigningKeyStatus::Retiring, Some(now + chrono::Duration::hours(1))),
+                key("expired", SigningKeyStatus::Retiring, Some(now - chrono::Duration::hours(1))),
+                key("next", SigningKeyStatus::Pending, None),
+                key("current", SigningKeyStatus::Active, None),
+            ],
+            generated_at: now,
+        };
+
+        assert_eq!(key_set.active().unwrap().kid, "current");
+        assert_eq!(key_set.verification_secrets(Some("old"), now), vec!["secret-old"]);
+        assert!(key_set.verification_secrets(Some("expired"), now).is_empty());
+        assert!(key_set.verification_secrets(Some("unknown"), now).is_empty());
+        assert_eq!(
+            key_set.verification_secrets(None, now),
+            vec!["secret-current", "secret-old", "secret-next"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_unknown_kid_refetches_key_set() {
+        let source = Arc::new(RotatingSource {
+            calls: AtomicUsize::new(0),
+        });
+        let client = SigningKeyClient::new(source.clone());
+
+        assert!(client.jwt_key_set(Some("k1")).await.unwrap().contains("k1"));
+        assert_eq!(source.calls.load(Ordering::SeqCst), 1);
+
+        // Fetched moments ago, so an unknown kid is not worth another request yet
+        assert!(!client.jwt_key_set(Some("k2")).await.unwrap().contains("k2"));
+        assert_eq!(source.calls.load(Ordering::SeqCst), 1);
+
+        let client = SigningKeyClient::new(source.clone()).with_ttl(Duration::ZERO);
+        let key_set = client.jwt_key_set(Some("k2")).await.unwrap();
+        assert_eq!(key_set.active().unwrap().kid, "k2");
+        assert!(key_set.contains("k1"));
+    }
+}
@@ -0,0 +1,375 @@
+// Distributed tracing
+//
+// Every service installs its subscriber through `init_tracing`, which adds an
+// OpenTelemetry layer exporting spans over OTLP when an endpoint is
+// configured. Trace context travels as W3C `traceparent`/`tracestate`
+// headers: `trace_http_requests` continues the caller's trace for incoming
+// requests, `with_trace_context` forwards it on outgoing reqwest calls, and
+// the Temporal builders copy it into workflow and activity headers so the
+// worker's spans join the same trace.
+
+use axum::{
+    extract::{MatchedPath, Request},
+    http::HeaderMap,
+    middleware::Next,
+    response::Response,
+};
+use opentelemetry::{
+    global,
+    propagation::Extractor,
+    sdk::{
+        propagation::TraceContextPropagator,
+        trace::{self as sdktrace, Sampler},
+        Resource,
+    },
+    Context, KeyValue,
+};
+use opentelemetry_otlp::WithExportConfig;
+use std::collections::HashMap;
+use std::future::Future;
+use tracing::{field, Instrument, Span};
+use tracing_opentelemetry::OpenTelemetrySpanExt;
+use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt, EnvFilter};
+
+use crate::{Result, ServiceError};
+
+/// Header carrying the W3C trace context
+pub const TRACEPARENT_HEADER: &str = "traceparent";
+
+#[derive(Debug, Clone)]
+pub struct TelemetryConfig {
+    /// Reported as the `service.name` resource attribute
+    pub service_name: String,
+    /// OTLP gRPC collector, e.g. "http://otel-collector:4317". Spans are only
+    /// logged locally when this is unset.
+    pub otlp_endpoint: Option<String>,
+    /// Fraction of new traces that are sampled; traces started upstream
+    /// follow the caller's sampling decision
+    pub sample_ratio: f64,
+}
+
+impl TelemetryConfig {
+    /// Reads the standard `OTEL_*` variables, falling back to the service's
+    /// own name
+    pub fn from_env(service_name: &str) -> Self {
+        Self {
+            service_name: std::env::var("OTEL_SERVICE_NAME").unwrap_or_else(|_| service_name.to_string()),
+            otlp_endpoint: std::env::var("OTEL_EXPORTER_OTLP_ENDPOINT")
+                .ok()
+                .filter(|endpoint| !endpoint.is_empty()),
+            sample_ratio: std::env::var("OTEL_TRACES_SAMPLER_ARG")
+                .ok()
+                .and_then(|ratio| ratio.parse().ok())
+                .unwrap_or(1.0),
+        }
+    }
+}
+
+/// Flushes buffered spans when dropped; hold it until the service exits
+#[must_use = "dropping the guard shuts the exporter down"]
+pub struct TelemetryGuard {
+    exporting: bool,
+}
+
+impl Drop for TelemetryGuard {
+    fn drop(&mut self) {
+        if self.exporting {
+            global::shutdown_tracer_provider();
+        }
+    }
+}
+
+/// Installs the global subscriber: `RUST_LOG` (or `default_filter`), console
+/// output and, when configured, OTLP export
+pub fn init_tracing(service_name: &str, default_filter: &str) -> Result<TelemetryGuard> {
+    let config = TelemetryConfig::from_env(service_name);
+    global::set_text_map_propagator(TraceContextPropagator::new());
+
+    let otel_layer = match &config.otlp_endpoint {
+        Some(endpoint) => Some(tracing_opentelemetry::layer().with_tracer(otlp_tracer(&config, endpoint)?)),
+        None => None,
+    };
+    let exporting = otel_layer.is_some();
+
+    tracing_subscriber::registry()
+        .with(EnvFilter::try_from_default_env().unwrap_or_else(|_| default_filter.into()))
+        .with(tracing_subscriber::fmt::layer())
+        .with(otel_layer)
+        .try_init()
+        .map_err(|e| ServiceError::Configuration(format!("Failed to install tracing subscriber: {}", e)))?;
+
+    if let Some(endpoint) = &config.otlp_endpoint {
+        tracing::info!(
+            service = %config.service_name,
+            endpoint = %endpoint,
+            sample_ratio = config.sample_ratio,
+            "Exporting traces over OTLP"
+        );
+    }
+
+    Ok(TelemetryGuard { exporting })
+}
+
+fn otlp_tracer(config: &TelemetryConfig, endpoint: &str) -> Result<sdktrace::Tracer> {
+    let sampler = Sampler::ParentBased(Box::new(Sampler::TraceIdRatioBased(config.sample_ratio)));
+
+    opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .tonic()
+                .with_endpoint(endpoint),
+        )
+        .with_trace_config(
+            sdktrace::config()
+                .with_sampler(sampler)
+                .with_resource(Resource::new(vec![KeyValue::new(
+                    "service.name",
+                    config.service_name.clone(),
+                )])),
+        )
+        .install_batch(opentelemetry::runtime::Tokio)
+        .map_err(|e| ServiceError::Configuration(format!("Failed to build OTLP exporter: {}", e)))
+}
+
+struct HeaderExtractor<'a>(&'a HeaderMap);
+
+impl Extractor for HeaderExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0.keys().map(|name| name.as_str()).collect()
+    }
+}
+
+/// Trace context of the current span as propagation headers. Empty when
+/// tracing isn't exporting, so callers can attach it unconditionally.
+pub fn current_trace_headers() -> HashMap<String, String> {
+    let context = Span::current().context();
+    let mut headers = HashMap::new();
+    global::get_text_map_propagator(|propagator| {
+        propagator.inject_context(&context, &mut headers)
+    });
+    headers
+}
+
+/// Remote parent context carried by HTTP request headers
+pub fn context_from_request_headers(headers: &HeaderMap) -> Context {
+    global::get_text_map_propagator(|propagator| propagator.extract(&HeaderExtractor(headers)))
+}
+
+/// Remote parent context carried by Temporal workflow or activity headers
+pub fn context_from_headers(headers: &HashMap<String, String>) -> Context {
+    global::get_text_map_propagator(|propagator| propagator.extract(headers))
+}
+
+/// Forwards the current trace context on outgoing service calls
+pub trait TracedRequestExt {
+    fn with_trace_context(self) -> Self;
+}
+
+impl TracedRequestExt for reqwest::RequestBuilder {
+    fn with_trace_context(self) -> Self {
+        current_trace_headers()
+            .into_iter()
+            .fold(self, |request, (name, value)| request.header(name, value))
+    }
+}
+
+/// gRPC request carrying the current trace context as metadata
+pub fn grpc_request<T>(message: T) -> tonic::Request<T> {
+    let mut request = tonic::Request::new(message);
+    for (name, value) in current_trace_headers() {
+        if let (Ok(name), Ok(value)) = (
+            name.parse::<tonic::metadata::MetadataKey<tonic::metadata::Ascii>>(),
+            value.parse(),
+        ) {
+            request.metadata_mut().insert(name, value);
+        }
+    }
+    request
+}
+
+struct MetadataExtractor<'a>(&'a tonic::metadata::MetadataMap);
+
+impl Extractor for MetadataExtractor<'_> {
+    fn get(&self, key: &str) -> Option<&str> {
+        self.0.get(key).and_then(|value| value.to_str().ok())
+    }
+
+    fn keys(&self) -> Vec<&str> {
+        self.0
+            .keys()
+            .filter_map(|key| match key {
+                tonic::metadata::KeyRef::Ascii(key) => Some(key.as_str()),
+                tonic::metadata::KeyRef::Binary(_) => None,
+            })
+            .collect()
+    }
+}
+
+/// Server span for a gRPC call, continuing the caller's trace
+pub fn grpc_span<T>(method: &str, request: &tonic::Request<T>) -> Span {
+    let span = tracing::info_span!(
+        "grpc.request",
+        otel.name = %method,
+        otel.kind = "server",
+        rpc.system = "grpc",
+        rpc.method = %method,
+    );
+    span.set_parent(global::get_text_map_propagator(|propagator| {
+        propagator.extract(&MetadataExtractor(request.metadata()))
+    }));
+    span
+}
+
+/// Server span for each request, continuing the caller's trace when the
+/// request carries one
+pub async fn trace_http_requests(request: Request, next: Next) -> Response {
+    let method = request.method().clone();
+    let route = request
+        .extensions()
+        .get::<MatchedPath>()
+        .map(|path| path.as_str().to_string())
+        .unwrap_or_else(|| request.uri().path().to_string());
+
+    let span = tracing::info_span!(
+        "http.request",
+        otel.name = %format!("{} {}", method, route),
+        otel.kind = "server",
+        otel.status_code = field::Empty,
+        http.request.method = %method,
+        http.route = %route,
+        url.path = %request.uri().path(),
+        http.response.status_code = field::Empty,
+    );
+    span.set_parent(context_from_request_headers(request.headers()));
+
+    let response = next.run(request).instrument(span.clone()).await;
+
+    span.record("http.response.status_code", response.status().as_u16());
+    if response.status().is_server_error() {
+        span.record("otel.status_code", "ERROR");
+    }
+    response
+}
+
+/// Client span for a SQL statement
+pub fn db_span(statement: &str) -> Span {
+    let operation = statement_operation(statement);
+    tracing::info_span!(
+        "db.query",
+        otel.name = %operation,
+        otel.kind = "client",
+        db.system = "postgresql",
+        db.operation = %operation,
+        db.statement = %statement,
+    )
+}
+
+/// Runs a SQLx query future inside its `db_span`
+pub async fn traced_query<F: Future>(statement: &str, query: F) -> F::Output {
+    query.instrument(db_span(statement)).await
+}
+
+fn statement_operation(statement: &str) -> String {
+    statement
+        .split_whitespace()
+        .next()
+        .map(|keyword| keyword.to_uppercase())
+        .unwrap_or_else(|| "QUERY".to_string())
+}
+
+/// Span a worker runs a workflow task in, parented by the headers the
+/// workflow was started with
+pub fn workflow_span(workflow_type: &str, workflow_id: &str, headers: &HashMap<String, String>) -> Span {
+    let span = tracing::info_span!(
+        "temporal.workflow",
+        otel.name = %format!("RunWorkflow:{}", workflow_type),
+        otel.kind = "server",
+        temporal.workflow_type = %workflow_type,
+        temporal.workflow_id = %workflow_id,
+    );
+    span.set_parent(context_from_headers(headers));
+    span
+}
+
+/// Span a worker runs an activity in, parented by the headers the workflow
+/// scheduled it with
+pub fn activity_span(activity_type: &str, activity_id: &str, headers: &HashMap<String, String>) -> Span {
+    let span = tracing::info_span!(
+        "temporal.activity",
+        otel.name = %format!("RunActivity:{}", activity_type),
+        otel.kind = "server",
+        temporal.activity_type = %activity_type,
+        temporal.activity_id = %activity_id,
+    );
+    span.set_parent(context_from_headers(headers));
+    span
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use axum::http::HeaderValue;
+    use opentelemetry::trace::{SpanContext, SpanId, TraceContextExt as _, TraceFlags, TraceId, TraceState};
+
+    const TRACEPARENT: &str = "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01";
+
+    fn install_propagator() {
+        global::set_text_map_propagator(TraceContextPropagator::new());
+    }
+
+    #[test]
+    fn test_request_headers_carry_remote_parent() {
+        install_propagator();
+        let mut headers = HeaderMap::new();
+        headers.insert(TRACEPARENT_HEADER, HeaderValue::from_static(TRACEPARENT));
+
+        let context = context_from_request_headers(&headers);
+        let span_context = context.span().span_context().clone();
+
+        assert!(span_context.is_remote());
+        assert_eq!(span_context.trace_id().to_string(), "4bf92f3577b34da6a3ce929d0e0e4736");
+        assert_eq!(span_context.span_id().to_string(), "00f067aa0ba902b7");
+    }
+
+    #[test]
+    fn test_workflow_headers_round_trip() {
+        install_propagator();
+        let parent = Context::new().with_remote_span_context(SpanContext::new(
+            TraceId::from_hex("4bf92f3577b34da6a3ce929d0e0e4736").unwrap(),
+            SpanId::from_hex("00f067aa0ba902b7").unwrap(),
+            TraceFlags::SAMPLED,
+            true,
+            TraceState::default(),
+        ));
+
+        let mut headers = HashMap::new();
+        global::get_text_map_propagator(|propagator| {
+            propagator.inject_context(&parent, &mut headers)
+        });
+        assert_eq!(headers.get(TRACEPARENT_HEADER).map(String::as_str), Some(TRACEPARENT));
+
+        let extracted = context_from_headers(&headers);
+        assert_eq!(
+            extracted.span().span_context().trace_id(),
+            parent.span().span_context().trace_id()
+        );
+    }
+
+    #[test]
+    fn test_no_trace_headers_without_a_trace() {
+        install_propagator();
+        assert!(current_trace_headers().is_empty());
+    }
+
+    #[test]
+    fn test_statement_operation() {
+        assert_eq!(statement_operation("select * from tenants where id = $1"), "SELECT");
+        assert_eq!(statement_operation("  INSERT INTO audit_events VALUES ($1)"), "INSERT");
+        assert_eq!(statement_operation(""), "QUERY");
+    }
+}
@@ -2,6 +2,7 @@ use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::time::Duration;
 
+use crate::telemetry::current_trace_headers;
 use crate::temporal::{ActivityError, TenantContext, UserContext};
 
 /// Activity execution context for ADX Core
@@ -112,6 +113,10 @@ pub struct ActivityExecutionOptions {
     
     /// Custom metadata
     pub metadata: HashMap<String, serde_json::Value>,
+    
+    /// Temporal header fields; carries the workflow's trace context
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
 }
 
 impl Default for ActivityExecutionOptions {
@@ -124,6 +129,7 @@ impl Default for ActivityExecutionOptions {
             retry_policy: None,
             tags: Vec::new(),
             metadata: HashMap::new(),
+            headers: HashMap::new(),
         }
     }
 }
@@ -445,10 +451,13 @@ where
             message: "Activity input is required".to_string(),
         })?;
         
+        let mut options = self.options;
+        options.headers.extend(current_trace_headers());
+        
         Ok(ActivityExecutionRequest {
             activity_type: self.activity_type,
             input,
-            options: self.options,
+            options,
         })
     }
 }
@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::time::Duration;
-use crate::temporal::TemporalError;
+use crate::temporal::{PriorityLane, TemporalError};
 
 /// Temporal configuration for ADX Core services
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -101,6 +102,17 @@ pub struct WorkerConfig {
     /// Task queue names this worker will poll
     pub task_queues: Vec<String>,
     
+    /// Per-queue caps on concurrent tasks, so work on one queue (a batch
+    /// queue, say) can't take every slot; queues without an entry may use
+    /// the worker-wide maximums
+    #[serde(default)]
+    pub task_queue_limits: HashMap<String, TaskQueueLimits>,
+    
+    /// Lane each task queue serves; queues without an entry are
+    /// interactive. Queues of each lane are polled by their own pool.
+    #[serde(default)]
+    pub task_queue_lanes: HashMap<String, PriorityLane>,
+    
     /// Enable sticky execution
     pub enable_sticky_execution: bool,
     
@@ -108,6 +120,46 @@ pub struct WorkerConfig {
     pub sticky_schedule_to_start_timeout: Duration,
 }
 
+/// Concurrent task caps for one task queue
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct TaskQueueLimits {
+    pub max_concurrent_workflow_tasks: usize,
+    pub max_concurrent_activity_tasks: usize,
+}
+
+impl WorkerConfig {
+    /// Limits for a task queue, never above the worker-wide maximums
+    pub fn limits_for(&self, task_queue: &str) -> TaskQueueLimits {
+        match self.task_queue_limits.get(task_queue) {
+            Some(limits) => TaskQueueLimits {
+                max_concurrent_workflow_tasks: limits
+                    .max_concurrent_workflow_tasks
+                    .min(self.max_concurrent_workflow_tasks),
+                max_concurrent_activity_tasks: limits
+                    .max_concurrent_activity_tasks
+                    .min(self.max_concurrent_activity_tasks),
+            },
+            None => TaskQueueLimits {
+                max_concurrent_workflow_tasks: self.max_concurrent_workflow_tasks,
+                max_concurrent_activity_tasks: self.max_concurrent_activity_tasks,
+            },
+        }
+    }
+
+    pub fn lane_of(&self, task_queue: &str) -> PriorityLane {
+        self.task_queue_lanes.get(task_queue).copied().unwrap_or_default()
+    }
+
+    /// Queues polled by the pool serving `lane`
+    pub fn pool_task_queues(&self, lane: PriorityLane) -> Vec<String> {
+        self.task_queues
+            .iter()
+            .filter(|queue| self.lane_of(queue) == lane)
+            .cloned()
+            .collect()
+    }
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkflowConfig {
     /// Default workflow execution timeout
@@ -195,6 +247,8 @@ impl Default for WorkerConfig {
             max_concurrent_activity_tasks: 200,
             identity: format!("adx-core-worker-{}", uuid::Uuid::new_v4()),
             task_queues: vec!["adx-core-default".to_string()],
+            task_queue_limits: HashMap::new(),
+            task_queue_lanes: HashMap::new(),
             enable_sticky_execution: true,
             sticky_schedule_to_start_timeout: Duration::from_secs(5),
         }
@@ -0,0 +1,199 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use crate::temporal::{AdxTemporalClient, TemporalError};
+
+/// Upper bound on history pages fetched for one run, so a pathologically
+/// long history can't keep a request open indefinitely
+const MAX_HISTORY_PAGES: usize = 100;
+
+/// One event from a workflow run's history, normalized from Temporal's JSON
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct HistoryEvent {
+    pub event_id: i64,
+    pub event_time: DateTime<Utc>,
+    /// PascalCase event type, such as `ActivityTaskScheduled`
+    pub event_type: String,
+    /// The event's `<type>EventAttributes` object
+    pub attributes: serde_json::Value,
+}
+
+impl HistoryEvent {
+    /// Parse an event as returned by Temporal's HTTP API
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, TemporalError> {
+        let malformed = |field: &str| TemporalError::SerializationError {
+            message: format!("History event is missing or has an invalid '{}'", field),
+        };
+
+        let event_id = value.get("eventId").and_then(json_i64).ok_or_else(|| malformed("eventId"))?;
+        let event_time = value
+            .get("eventTime")
+            .and_then(|v| v.as_str())
+            .and_then(|s| DateTime::parse_from_rfc3339(s).ok())
+            .map(|t| t.with_timezone(&Utc))
+            .ok_or_else(|| malformed("eventTime"))?;
+        let event_type = value
+            .get("eventType")
+            .and_then(|v| v.as_str())
+            .map(normalize_event_type)
+            .ok_or_else(|| malformed("eventType"))?;
+
+        let attributes = value
+            .as_object()
+            .and_then(|event| {
+                event
+                    .iter()
+                    .find(|(key, _)| key.ends_with("EventAttributes"))
+                    .map(|(_, attributes)| attributes.clone())
+            })
+            .unwrap_or(serde_json::Value::Null);
+
+        Ok(Self {
+            event_id,
+            event_time,
+            event_type,
+            attributes,
+        })
+    }
+
+    /// An int64 attribute; the HTTP API encodes these as strings
+    pub fn attribute_i64(&self, name: &str) -> Option<i64> {
+        self.attributes.get(name).and_then(json_i64)
+    }
+
+    pub fn attribute_str(&self, name: &str) -> Option<&str> {
+        self.attributes.get(name).and_then(|v| v.as_str())
+    }
+}
+
+/// Decode a `Payloads` attribute into JSON values. Relies on the HTTP API's
+/// shorthand payload encoding, where JSON payloads are inlined as-is.
+pub fn decode_payloads(payloads: &serde_json::Value) -> Vec<serde_json::Value> {
+    payloads
+        .get("payloads")
+        .and_then(|p| p.as_array())
+        .cloned()
+        .unwrap_or_default()
+}
+
+/// `EVENT_TYPE_ACTIVITY_TASK_SCHEDULED` -> `ActivityTaskScheduled`. Names
+/// already in PascalCase are returned unchanged.
+fn normalize_event_type(raw: &str) -> String {
+    let Some(name) = raw.strip_prefix("EVENT_TYPE_") else {
+        return raw.to_string();
+    };
+
+    name.split('_')
+        .filter(|word| !word.is_empty())
+        .map(|word| {
+            let lower = word.to_ascii_lowercase();
+            let mut chars = lower.chars();
+            match chars.next() {
+                Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+                None => String::new(),
+            }
+        })
+        .collect()
+}
+
+fn json_i64(value: &serde_json::Value) -> Option<i64> {
+    value
+        .as_i64()
+        .or_else(|| value.as_str().and_then(|s| s.parse().ok()))
+}
+
+impl AdxTemporalClient {
+    /// Fetch the full event history of a workflow run, following page tokens.
+    /// Without a run id the latest run is read.
+    pub async fn get_workflow_history(
+        &self,
+        workflow_id: &str,
+        run_id: Option<&str>,
+    ) -> Result<Vec<HistoryEvent>, TemporalError> {
+        info!(
+            workflow_id = workflow_id,
+            run_id = ?run_id,
+            client_id = %self.client_id(),
+            "Fetching workflow history with HTTP communication"
+        );
+
+        let url = format!(
+            "http://{}/api/v1/namespaces/{}/workflows/{}/history",
+            self.config().server_address,
+            self.namespace(),
+            workflow_id
+        );
+
+        let mut events = Vec::new();
+        let mut page_token: Option<String> = None;
+
+        for _ in 0..MAX_HISTORY_PAGES {
+            let mut query: Vec<(&str, &str)> = Vec::new();
+            if let Some(run_id) = run_id {
+                query.push(("execution.runId", run_id));
+            }
+            if let Some(token) = page_token.as_deref() {
+                query.push(("nextPageToken", token));
+            }
+
+            let response = self
+                .http_client()
+                .get(&url)
+                .query(&query)
+                .send()
+                .await
+                .map_err(|e| TemporalError::ConnectionError {
+                    message: format!("Failed to fetch workflow history: {}", e),
+                })?;
+
+            if response.status() == reqwest::StatusCode::NOT_FOUND {
+                return Err(TemporalError::WorkflowNotFound {
+                    workflow_id: workflow_id.to_string(),
+                });
+            }
+            if !response.status().is_success() {
+                return Err(TemporalError::Generic {
+                    message: format!(
+                        "Temporal returned {} for workflow history of {}",
+                        response.status(),
+                        workflow_id
+                    ),
+                });
+            }
+
+            let page: serde_json::Value = response.json().await.map_err(|e| {
+                TemporalError::SerializationError {
+                    message: format!("Failed to decode workflow history: {}", e),
+                }
+            })?;
+
+            if let Some(page_events) = page
+                .get("history")
+                .and_then(|h| h.get("events"))
+                .and_then(|e| e.as_array())
+            {
+                for event in page_events {
+                    events.push(HistoryEvent::from_json(event)?);
+                }
+            }
+
+            page_token = page
+                .get("nextPageToken")
+                .and_then(|t| t.as_str())
+                .filter(|t| !t.is_empty())
+                .map(str::to_string);
+            if page_token.is_none() {
+                break;
+            }
+        }
+
+        debug!(
+            workflow_id = workflow_id,
+            events = events.len(),
+            "Workflow history fetched"
+        );
+
+        Ok(events)
+    }
+}
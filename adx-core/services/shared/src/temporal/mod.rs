@@ -4,7 +4,12 @@
 pub mod client;
 pub mod config;
 pub mod error;
+pub mod history;
+pub mod priority;
+pub mod replay;
 pub mod retry;
+pub mod schedule;
+pub mod search;
 pub mod versioning;
 pub mod workflow;
 pub mod activity;
@@ -19,7 +24,12 @@ pub mod sdk_test;
 pub use client::*;
 pub use config::*;
 pub use error::*;
+pub use history::*;
+pub use priority::*;
+pub use replay::*;
 pub use retry::*;
+pub use schedule::*;
+pub use search::*;
 pub use versioning::*;
 pub use workflow::*;
 pub use activity::*;
@@ -0,0 +1,164 @@
+use serde::{Deserialize, Serialize};
+use std::time::Duration;
+use tokio::sync::watch;
+use tracing::debug;
+
+use crate::temporal::{TemporalError, WorkflowPriority};
+
+/// Lane a workflow run is scheduled in. Interactive runs back user-facing
+/// operations such as tenant switching; batch runs are bulk work that may
+/// wait for them. Each lane's task queues are polled by their own worker
+/// pool.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum PriorityLane {
+    #[default]
+    Interactive,
+    Batch,
+}
+
+impl PriorityLane {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            PriorityLane::Interactive => "interactive",
+            PriorityLane::Batch => "batch",
+        }
+    }
+
+    pub fn parse(value: &str) -> Result<Self, TemporalError> {
+        match value {
+            "interactive" => Ok(PriorityLane::Interactive),
+            "batch" => Ok(PriorityLane::Batch),
+            other => Err(TemporalError::ConfigurationError {
+                message: format!("Unknown priority lane: {}", other),
+            }),
+        }
+    }
+
+    /// The lower of two lanes
+    pub fn min(self, other: Self) -> Self {
+        if self == PriorityLane::Batch || other == PriorityLane::Batch {
+            PriorityLane::Batch
+        } else {
+            PriorityLane::Interactive
+        }
+    }
+}
+
+impl WorkflowPriority {
+    /// Low-priority runs are batch work; everything else is interactive
+    pub fn lane(&self) -> PriorityLane {
+        match self {
+            WorkflowPriority::Low => PriorityLane::Batch,
+            WorkflowPriority::Normal | WorkflowPriority::High | WorkflowPriority::Critical => {
+                PriorityLane::Interactive
+            }
+        }
+    }
+}
+
+/// Tracks interactive runs in flight so batch runs can step aside for them.
+/// Batch runs call `yield_to_interactive` between units of work; it returns
+/// once no interactive run is in flight, or after `max_wait` so a steady
+/// stream of interactive work can't starve batch work for good.
+pub struct PriorityLanes {
+    interactive: watch::Sender<usize>,
+    max_wait: Duration,
+}
+
+impl PriorityLanes {
+    pub fn new(max_wait: Duration) -> Self {
+        let (interactive, _) = watch::channel(0);
+        Self { interactive, max_wait }
+    }
+
+    /// Count an interactive run as in flight until the guard is dropped
+    pub fn admit_interactive(&self) -> InteractiveGuard {
+        self.interactive.send_modify(|count| *count += 1);
+        InteractiveGuard {
+            interactive: self.interactive.clone(),
+        }
+    }
+
+    pub fn interactive_in_flight(&self) -> usize {
+        *self.interactive.borrow()
+    }
+
+    /// Wait while interactive runs are in flight, for at most `max_wait`.
+    /// Returns how long the caller was held back.
+    pub async fn yield_to_interactive(&self) -> Duration {
+        if self.interactive_in_flight() == 0 {
+            return Duration::ZERO;
+        }
+
+        let started = std::time::Instant::now();
+        let mut interactive = self.interactive.subscribe();
+        let _ = tokio::time::timeout(self.max_wait, interactive.wait_for(|count| *count == 0)).await;
+        let waited = started.elapsed();
+
+        debug!(waited_ms = waited.as_millis() as u64, "Batch work yielded to interactive runs");
+        waited
+    }
+}
+
+impl Default for PriorityLanes {
+    fn default() -> Self {
+        Self::new(Duration::from_secs(30))
+    }
+}
+
+/// Marks an interactive run as in flight
+pub struct InteractiveGuard {
+    interactive: watch::Sender<usize>,
+}
+
+impl Drop for InteractiveGuard {
+    fn drop(&mut self) {
+        self.interactive.send_modify(|count| *count = count.saturating_sub(1));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_batch_waits_for_interactive_runs() {
+        let lanes = std::sync::Arc::new(PriorityLanes::new(Duration::from_secs(5)));
+        assert_eq!(lanes.yield_to_interactive().await, Duration::ZERO);
+
+        let guard = lanes.admit_interactive();
+        assert_eq!(lanes.interactive_in_flight(), 1);
+
+        let release = tokio::spawn(async move {
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            drop(guard);
+        });
+        let waited = lanes.yield_to_interactive().await;
+        release.await.unwrap();
+
+        assert!(waited >= Duration::from_millis(40));
+        assert!(waited < Duration::from_secs(5));
+        assert_eq!(lanes.interactive_in_flight(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_yield_is_bounded() {
+        let lanes = PriorityLanes::new(Duration::from_millis(20));
+        let _guard = lanes.admit_interactive();
+
+        let waited = lanes.yield_to_interactive().await;
+        assert!(waited >= Duration::from_millis(20));
+        assert!(waited < Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_lanes() {
+        assert_eq!(PriorityLane::Interactive.min(PriorityLane::Batch), PriorityLane::Batch);
+        assert_eq!(PriorityLane::Interactive.min(PriorityLane::Interactive), PriorityLane::Interactive);
+        assert_eq!(WorkflowPriority::Low.lane(), PriorityLane::Batch);
+        assert_eq!(WorkflowPriority::Critical.lane(), PriorityLane::Interactive);
+        assert_eq!(PriorityLane::parse("batch").unwrap(), PriorityLane::Batch);
+        assert!(PriorityLane::parse("urgent").is_err());
+    }
+}
@@ -0,0 +1,403 @@
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use thiserror::Error;
+use tracing::info;
+
+use crate::temporal::{decode_payloads, AdxTemporalClient, HistoryEvent, TemporalError};
+
+/// Extension of recorded history files
+pub const HISTORY_FILE_EXTENSION: &str = "json";
+
+/// How an activity or a whole run ended
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum RecordedOutcome {
+    Completed { result: serde_json::Value },
+    Failed { message: String },
+}
+
+impl RecordedOutcome {
+    pub fn is_completed(&self) -> bool {
+        matches!(self, RecordedOutcome::Completed { .. })
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RecordedActivity {
+    pub activity_type: String,
+    pub input: serde_json::Value,
+    /// Missing when the run was recorded while the activity was in flight
+    pub outcome: Option<RecordedOutcome>,
+}
+
+/// A workflow run reduced to what replay compares: its input, the
+/// activities it scheduled in order and how it ended. Recorded from
+/// Temporal and kept as JSON next to the tests that replay it.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct RecordedHistory {
+    pub workflow_id: String,
+    pub run_id: Option<String>,
+    pub workflow_type: String,
+    pub input: serde_json::Value,
+    pub activities: Vec<RecordedActivity>,
+    /// Missing while the run is still open
+    pub outcome: Option<RecordedOutcome>,
+}
+
+impl RecordedHistory {
+    /// Build a recording from a run's history events
+    pub fn from_events(workflow_id: &str, events: &[HistoryEvent]) -> Result<Self, TemporalError> {
+        let started = events
+            .iter()
+            .find(|e| e.event_type == "WorkflowExecutionStarted")
+            .ok_or_else(|| TemporalError::SerializationError {
+                message: format!("History of {} has no WorkflowExecutionStarted event", workflow_id),
+            })?;
+
+        let mut history = Self {
+            workflow_id: workflow_id.to_string(),
+            run_id: started.attribute_str("originalExecutionRunId").map(str::to_string),
+            workflow_type: nested_str(&started.attributes, &["workflowType", "name"])
+                .unwrap_or_default()
+                .to_string(),
+            input: first_payload(started.attributes.get("input")),
+            activities: Vec::new(),
+            outcome: None,
+        };
+
+        // Scheduled event id -> index into `activities`
+        let mut scheduled = HashMap::new();
+        for event in events {
+            match event.event_type.as_str() {
+                "ActivityTaskScheduled" => {
+                    scheduled.insert(event.event_id, history.activities.len());
+                    history.activities.push(RecordedActivity {
+                        activity_type: nested_str(&event.attributes, &["activityType", "name"])
+                            .unwrap_or_default()
+                            .to_string(),
+                        input: first_payload(event.attributes.get("input")),
+                        outcome: None,
+                    });
+                }
+                "ActivityTaskCompleted" | "ActivityTaskFailed" | "ActivityTaskTimedOut" | "ActivityTaskCanceled" => {
+                    let index = event
+                        .attribute_i64("scheduledEventId")
+                        .and_then(|id| scheduled.get(&id).copied());
+                    if let Some(activity) = index.and_then(|index| history.activities.get_mut(index)) {
+                        activity.outcome = Some(outcome_of(event));
+                    }
+                }
+                "WorkflowExecutionCompleted"
+                | "WorkflowExecutionFailed"
+                | "WorkflowExecutionTimedOut"
+                | "WorkflowExecutionCanceled"
+                | "WorkflowExecutionTerminated" => {
+                    history.outcome = Some(outcome_of(event));
+                }
+                _ => {}
+            }
+        }
+
+        Ok(history)
+    }
+
+    pub fn load(path: &Path) -> Result<Self, TemporalError> {
+        let contents = std::fs::read_to_string(path).map_err(|e| TemporalError::SerializationError {
+            message: format!("Failed to read {}: {}", path.display(), e),
+        })?;
+        serde_json::from_str(&contents).map_err(|e| TemporalError::SerializationError {
+            message: format!("Invalid history in {}: {}", path.display(), e),
+        })
+    }
+
+    /// Write the recording to `<dir>/<workflow_id>.json`
+    pub fn save(&self, dir: &Path) -> Result<PathBuf, TemporalError> {
+        let path = dir.join(format!("{}.{}", self.workflow_id, HISTORY_FILE_EXTENSION));
+        let contents = serde_json::to_string_pretty(self).map_err(|e| TemporalError::SerializationError {
+            message: e.to_string(),
+        })?;
+        std::fs::write(&path, contents + "\n").map_err(|e| TemporalError::SerializationError {
+            message: format!("Failed to write {}: {}", path.display(), e),
+        })?;
+        Ok(path)
+    }
+}
+
+/// Every recording in a directory, sorted by file name
+pub fn load_recorded_histories(dir: &Path) -> Result<Vec<(PathBuf, RecordedHistory)>, TemporalError> {
+    let entries = std::fs::read_dir(dir).map_err(|e| TemporalError::SerializationError {
+        message: format!("Failed to read {}: {}", dir.display(), e),
+    })?;
+
+    let mut paths: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().and_then(|ext| ext.to_str()) == Some(HISTORY_FILE_EXTENSION))
+        .collect();
+    paths.sort();
+
+    paths
+        .into_iter()
+        .map(|path| RecordedHistory::load(&path).map(|history| (path, history)))
+        .collect()
+}
+
+impl AdxTemporalClient {
+    /// Record a run's history for replay. Without a run id the latest run
+    /// is recorded.
+    pub async fn record_history(&self, workflow_id: &str, run_id: Option<&str>) -> Result<RecordedHistory, TemporalError> {
+        let events = self.get_workflow_history(workflow_id, run_id).await?;
+        let history = RecordedHistory::from_events(workflow_id, &events)?;
+
+        info!(
+            workflow_id = workflow_id,
+            workflow_type = %history.workflow_type,
+            activities = history.activities.len(),
+            "Recorded workflow history for replay"
+        );
+        Ok(history)
+    }
+}
+
+/// A way new workflow code departs from a recorded run. Any of these means
+/// runs started on the old code can't be replayed by the new code.
+#[derive(Debug, Clone, Error, PartialEq)]
+pub enum NondeterminismError {
+    #[error("Activity {index} changed from {recorded} to {replayed}")]
+    ActivityChanged { index: usize, recorded: String, replayed: String },
+
+    #[error("Activity {index} ({activity_type}) was called with different input")]
+    InputChanged {
+        index: usize,
+        activity_type: String,
+        recorded: serde_json::Value,
+        replayed: serde_json::Value,
+    },
+
+    #[error("Activity {index} ({activity_type}) was not in the recorded run")]
+    ExtraActivity { index: usize, activity_type: String },
+
+    #[error("Activity {index} ({activity_type}) has no recorded outcome to replay")]
+    OutcomeMissing { index: usize, activity_type: String },
+
+    #[error("Replay stopped after {replayed} of {recorded} recorded activities")]
+    MissingActivities { recorded: usize, replayed: usize },
+
+    #[error("Recorded run {recorded} but replay {replayed}")]
+    OutcomeChanged { recorded: &'static str, replayed: &'static str },
+}
+
+/// Walks a recording as the workflow is replayed: each activity call must
+/// match the next recorded one, and gets its recorded outcome back. The
+/// first mismatch is kept, so a workflow that swallows the error still
+/// fails the replay.
+pub struct ReplayCursor {
+    history: RecordedHistory,
+    state: Mutex<CursorState>,
+}
+
+#[derive(Default)]
+struct CursorState {
+    next: usize,
+    mismatch: Option<NondeterminismError>,
+}
+
+impl ReplayCursor {
+    pub fn new(history: RecordedHistory) -> Self {
+        Self {
+            history,
+            state: Mutex::new(CursorState::default()),
+        }
+    }
+
+    pub fn history(&self) -> &RecordedHistory {
+        &self.history
+    }
+
+    /// The recorded outcome of the next activity, if the call matches it
+    pub fn next_activity(
+        &self,
+        activity_type: &str,
+        input: &serde_json::Value,
+    ) -> Result<RecordedOutcome, NondeterminismError> {
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(mismatch) = &state.mismatch {
+            return Err(mismatch.clone());
+        }
+
+        let index = state.next;
+        state.next += 1;
+        let result = match self.history.activities.get(index) {
+            None => Err(NondeterminismError::ExtraActivity {
+                index,
+                activity_type: activity_type.to_string(),
+            }),
+            Some(recorded) if recorded.activity_type != activity_type => Err(NondeterminismError::ActivityChanged {
+                index,
+                recorded: recorded.activity_type.clone(),
+                replayed: activity_type.to_string(),
+            }),
+            Some(recorded) if &recorded.input != input => Err(NondeterminismError::InputChanged {
+                index,
+                activity_type: activity_type.to_string(),
+                recorded: recorded.input.clone(),
+                replayed: input.clone(),
+            }),
+            Some(recorded) => recorded.outcome.clone().ok_or_else(|| NondeterminismError::OutcomeMissing {
+                index,
+                activity_type: activity_type.to_string(),
+            }),
+        };
+
+        if let Err(mismatch) = &result {
+            state.mismatch = Some(mismatch.clone());
+        }
+        result
+    }
+
+    /// Check the replay as a whole once the workflow has returned. A run
+    /// recorded while still open only has to replay the activities so far.
+    pub fn finish(&self, completed: bool) -> Result<(), NondeterminismError> {
+        let state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        if let Some(mismatch) = &state.mismatch {
+            return Err(mismatch.clone());
+        }
+
+        let Some(outcome) = &self.history.outcome else {
+            return Ok(());
+        };
+        if state.next < self.history.activities.len() {
+            return Err(NondeterminismError::MissingActivities {
+                recorded: self.history.activities.len(),
+                replayed: state.next,
+            });
+        }
+        if outcome.is_completed() != completed {
+            let describe = |completed: bool| if completed { "completed" } else { "failed" };
+            return Err(NondeterminismError::OutcomeChanged {
+                recorded: describe(outcome.is_completed()),
+                replayed: describe(completed),
+            });
+        }
+        Ok(())
+    }
+}
+
+fn outcome_of(event: &HistoryEvent) -> RecordedOutcome {
+    match event.event_type.as_str() {
+        "ActivityTaskCompleted" | "WorkflowExecutionCompleted" => RecordedOutcome::Completed {
+            result: first_payload(event.attributes.get("result")),
+        },
+        other => RecordedOutcome::Failed {
+            message: nested_str(&event.attributes, &["failure", "message"])
+                .map(str::to_string)
+                .unwrap_or_else(|| other.to_string()),
+        },
+    }
+}
+
+/// Workflows and activities here take and return a single payload
+fn first_payload(payloads: Option<&serde_json::Value>) -> serde_json::Value {
+    payloads
+        .map(decode_payloads)
+        .and_then(|payloads| payloads.into_iter().next())
+        .unwrap_or(serde_json::Value::Null)
+}
+
+fn nested_str<'a>(value: &'a serde_json::Value, path: &[&str]) -> Option<&'a str> {
+    path.iter().try_fold(value, |value, key| value.get(key))?.as_str()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn event(event_id: i64, event_type: &str, attributes: serde_json::Value) -> HistoryEvent {
+        HistoryEvent {
+            event_id,
+            event_time: chrono::Utc::now(),
+            event_type: event_type.to_string(),
+            attributes,
+        }
+    }
+
+    fn recorded_run() -> RecordedHistory {
+        let events = vec![
+            event(1, "WorkflowExecutionStarted", json!({
+                "workflowType": { "name": "tenant_switching_workflow" },
+                "originalExecutionRunId": "run-1",
+                "input": { "payloads": [{ "user_id": "u1" }] },
+            })),
+            event(5, "ActivityTaskScheduled", json!({
+                "activityType": { "name": "validate_tenant_access" },
+                "input": { "payloads": [{ "user_id": "u1" }] },
+            })),
+            event(7, "ActivityTaskCompleted", json!({
+                "scheduledEventId": "5",
+                "result": { "payloads": [{ "has_access": true }] },
+            })),
+            event(11, "ActivityTaskScheduled", json!({
+                "activityType": { "name": "get_tenant_context" },
+                "input": { "payloads": [{ "tenant_id": "t2" }] },
+            })),
+            event(13, "ActivityTaskFailed", json!({
+                "scheduledEventId": "11",
+                "failure": { "message": "tenant service unavailable" },
+            })),
+            event(16, "WorkflowExecutionFailed", json!({
+                "failure": { "message": "tenant service unavailable" },
+            })),
+        ];
+        RecordedHistory::from_events("switch-1", &events).unwrap()
+    }
+
+    #[test]
+    fn test_history_from_events() {
+        let history = recorded_run();
+
+        assert_eq!(history.workflow_type, "tenant_switching_workflow");
+        assert_eq!(history.run_id.as_deref(), Some("run-1"));
+        assert_eq!(history.input, json!({ "user_id": "u1" }));
+        assert_eq!(history.activities.len(), 2);
+        assert_eq!(
+            history.activities[0].outcome,
+            Some(RecordedOutcome::Completed { result: json!({ "has_access": true }) })
+        );
+        assert_eq!(
+            history.activities[1].outcome,
+            Some(RecordedOutcome::Failed { message: "tenant service unavailable".to_string() })
+        );
+        assert!(!history.outcome.unwrap().is_completed());
+    }
+
+    #[test]
+    fn test_matching_replay_passes() {
+        let cursor = ReplayCursor::new(recorded_run());
+
+        assert!(cursor.next_activity("validate_tenant_access", &json!({ "user_id": "u1" })).unwrap().is_completed());
+        assert!(!cursor.next_activity("get_tenant_context", &json!({ "tenant_id": "t2" })).unwrap().is_completed());
+        assert_eq!(cursor.finish(false), Ok(()));
+    }
+
+    #[test]
+    fn test_changes_are_detected() {
+        let cursor = ReplayCursor::new(recorded_run());
+        let error = cursor.next_activity("get_tenant_context", &json!({ "tenant_id": "t2" })).unwrap_err();
+        assert!(matches!(error, NondeterminismError::ActivityChanged { index: 0, .. }));
+        // The first mismatch sticks even if the workflow carries on
+        assert_eq!(cursor.finish(false), Err(error));
+
+        let cursor = ReplayCursor::new(recorded_run());
+        let error = cursor.next_activity("validate_tenant_access", &json!({ "user_id": "u2" })).unwrap_err();
+        assert!(matches!(error, NondeterminismError::InputChanged { index: 0, .. }));
+
+        let cursor = ReplayCursor::new(recorded_run());
+        cursor.next_activity("validate_tenant_access", &json!({ "user_id": "u1" })).unwrap();
+        assert_eq!(
+            cursor.finish(true),
+            Err(NondeterminismError::MissingActivities { recorded: 2, replayed: 1 })
+        );
+    }
+}
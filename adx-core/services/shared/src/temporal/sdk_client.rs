@@ -432,12 +432,13 @@ impl TemporalSDKClient {
             "Creating Temporal worker"
         );
         
+        let limits = self.config.worker.limits_for(task_queue);
         let worker_config = WorkerConfig {
             namespace: self.namespace.clone(),
             task_queue: task_queue.to_string(),
             worker_build_id: format!("{}-worker", self.client_id),
-            max_concurrent_workflow_tasks: self.config.worker.max_concurrent_workflow_tasks,
-            max_concurrent_activity_tasks: self.config.worker.max_concurrent_activity_tasks,
+            max_concurrent_workflow_tasks: limits.max_concurrent_workflow_tasks,
+            max_concurrent_activity_tasks: limits.max_concurrent_activity_tasks,
         };
         
         TemporalWorker::new(worker_config, self.core_client.clone())
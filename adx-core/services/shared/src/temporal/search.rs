@@ -0,0 +1,464 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use tracing::{debug, info};
+
+use crate::temporal::client::{WorkflowHandle, WorkflowStatus};
+use crate::temporal::{AdxTemporalClient, PriorityLane, TemporalError};
+
+pub const TENANT_ID_ATTRIBUTE: &str = "TenantId";
+pub const USER_ID_ATTRIBUTE: &str = "UserId";
+pub const BUSINESS_KEYS_ATTRIBUTE: &str = "BusinessKeys";
+pub const PRIORITY_LANE_ATTRIBUTE: &str = "PriorityLane";
+/// Built into Temporal, so it is indexed without being registered
+pub const WORKFLOW_TYPE_ATTRIBUTE: &str = "WorkflowType";
+const EXECUTION_STATUS_ATTRIBUTE: &str = "ExecutionStatus";
+
+/// Custom search attributes every namespace needs, with their index types
+pub const CUSTOM_SEARCH_ATTRIBUTES: [(&str, &str); 4] = [
+    (TENANT_ID_ATTRIBUTE, "INDEXED_VALUE_TYPE_KEYWORD"),
+    (USER_ID_ATTRIBUTE, "INDEXED_VALUE_TYPE_KEYWORD"),
+    (BUSINESS_KEYS_ATTRIBUTE, "INDEXED_VALUE_TYPE_KEYWORD_LIST"),
+    (PRIORITY_LANE_ATTRIBUTE, "INDEXED_VALUE_TYPE_KEYWORD"),
+];
+
+/// Upper bound on executions returned per list page
+pub const MAX_LIST_PAGE_SIZE: u32 = 100;
+
+/// Search attributes set on every workflow ADX starts
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct WorkflowSearchAttributes {
+    pub tenant_id: String,
+    /// The user who started the workflow
+    pub user_id: Option<String>,
+    pub workflow_type: String,
+    /// Domain identifiers the run can be found by, such as `template:<id>`
+    pub business_keys: Vec<String>,
+    /// Lane the run was started in
+    pub lane: PriorityLane,
+}
+
+impl WorkflowSearchAttributes {
+    pub fn new(tenant_id: &str, workflow_type: &str) -> Self {
+        Self {
+            tenant_id: tenant_id.to_string(),
+            workflow_type: workflow_type.to_string(),
+            ..Self::default()
+        }
+    }
+
+    /// Set the initiating user
+    pub fn user(mut self, user_id: Option<&str>) -> Self {
+        self.user_id = user_id.map(str::to_string);
+        self
+    }
+
+    /// Add a business key
+    pub fn business_key(mut self, key: impl Into<String>) -> Self {
+        self.business_keys.push(key.into());
+        self
+    }
+
+    pub fn lane(mut self, lane: PriorityLane) -> Self {
+        self.lane = lane;
+        self
+    }
+
+    /// The attributes as indexed fields. `WorkflowType` is left out because
+    /// Temporal sets it itself.
+    pub fn indexed_fields(&self) -> serde_json::Value {
+        let mut fields = serde_json::Map::new();
+        fields.insert(TENANT_ID_ATTRIBUTE.to_string(), self.tenant_id.clone().into());
+        if let Some(user_id) = &self.user_id {
+            fields.insert(USER_ID_ATTRIBUTE.to_string(), user_id.clone().into());
+        }
+        if !self.business_keys.is_empty() {
+            fields.insert(BUSINESS_KEYS_ATTRIBUTE.to_string(), self.business_keys.clone().into());
+        }
+        fields.insert(PRIORITY_LANE_ATTRIBUTE.to_string(), self.lane.as_str().into());
+        serde_json::Value::Object(fields)
+    }
+
+    /// Read the attributes back from a visibility record's indexed fields.
+    /// Relies on the HTTP API's shorthand payload encoding.
+    pub fn from_indexed_fields(workflow_type: &str, fields: &serde_json::Value) -> Self {
+        let string = |name: &str| fields.get(name).and_then(|v| v.as_str()).map(str::to_string);
+
+        Self {
+            tenant_id: string(TENANT_ID_ATTRIBUTE).unwrap_or_default(),
+            user_id: string(USER_ID_ATTRIBUTE),
+            workflow_type: workflow_type.to_string(),
+            business_keys: fields
+                .get(BUSINESS_KEYS_ATTRIBUTE)
+                .and_then(|v| v.as_array())
+                .map(|keys| keys.iter().filter_map(|k| k.as_str().map(str::to_string)).collect())
+                .unwrap_or_default(),
+            lane: string(PRIORITY_LANE_ATTRIBUTE)
+                .and_then(|lane| PriorityLane::parse(&lane).ok())
+                .unwrap_or_default(),
+        }
+    }
+}
+
+/// Which executions of one tenant to list
+#[derive(Debug, Clone, Default)]
+pub struct WorkflowListFilter {
+    pub tenant_id: String,
+    pub status: Option<WorkflowStatus>,
+    pub workflow_type: Option<String>,
+    /// Only runs started by this user
+    pub user_id: Option<String>,
+    pub business_key: Option<String>,
+}
+
+impl WorkflowListFilter {
+    /// The filter as a Temporal visibility query
+    pub fn query(&self) -> String {
+        let mut clauses = vec![format!("{} = {}", TENANT_ID_ATTRIBUTE, quote(&self.tenant_id))];
+        if let Some(status) = &self.status {
+            clauses.push(format!("{} = {}", EXECUTION_STATUS_ATTRIBUTE, quote(visibility_status(status))));
+        }
+        if let Some(workflow_type) = &self.workflow_type {
+            clauses.push(format!("{} = {}", WORKFLOW_TYPE_ATTRIBUTE, quote(workflow_type)));
+        }
+        if let Some(user_id) = &self.user_id {
+            clauses.push(format!("{} = {}", USER_ID_ATTRIBUTE, quote(user_id)));
+        }
+        if let Some(key) = &self.business_key {
+            clauses.push(format!("{} = {}", BUSINESS_KEYS_ATTRIBUTE, quote(key)));
+        }
+
+        clauses.join(" AND ")
+    }
+}
+
+/// A visibility record of one run
+#[derive(Debug, Clone)]
+pub struct WorkflowExecutionSummary {
+    pub workflow_id: String,
+    pub run_id: String,
+    pub workflow_type: String,
+    pub status: WorkflowStatus,
+    pub task_queue: Option<String>,
+    pub start_time: DateTime<Utc>,
+    pub close_time: Option<DateTime<Utc>>,
+    pub search_attributes: WorkflowSearchAttributes,
+}
+
+impl WorkflowExecutionSummary {
+    /// Parse an execution as returned by Temporal's HTTP list API
+    pub fn from_json(value: &serde_json::Value) -> Result<Self, TemporalError> {
+        let malformed = |field: &str| TemporalError::SerializationError {
+            message: format!("Workflow execution is missing or has an invalid '{}'", field),
+        };
+        let str_at = |path: &[&str]| {
+            path.iter()
+                .try_fold(value, |v, key| v.get(key))
+                .and_then(|v| v.as_str())
+        };
+
+        let workflow_type = str_at(&["type", "name"]).ok_or_else(|| malformed("type"))?;
+        let status = str_at(&["status"]).and_then(parse_api_status).ok_or_else(|| malformed("status"))?;
+        let start_time = str_at(&["startTime"]).and_then(parse_time).ok_or_else(|| malformed("startTime"))?;
+        let fields = value
+            .get("searchAttributes")
+            .and_then(|a| a.get("indexedFields"))
+            .cloned()
+            .unwrap_or(serde_json::Value::Null);
+
+        Ok(Self {
+            workflow_id: str_at(&["execution", "workflowId"]).ok_or_else(|| malformed("execution"))?.to_string(),
+            run_id: str_at(&["execution", "runId"]).ok_or_else(|| malformed("execution"))?.to_string(),
+            workflow_type: workflow_type.to_string(),
+            status,
+            task_queue: str_at(&["taskQueue"]).map(str::to_string),
+            start_time,
+            close_time: str_at(&["closeTime"]).and_then(parse_time),
+            search_attributes: WorkflowSearchAttributes::from_indexed_fields(workflow_type, &fields),
+        })
+    }
+}
+
+/// One page of a visibility listing
+#[derive(Debug, Clone)]
+pub struct WorkflowExecutionPage {
+    pub executions: Vec<WorkflowExecutionSummary>,
+    /// Token for the next page; `None` on the last page
+    pub next_page_token: Option<String>,
+}
+
+/// Visibility query for running executions, of one tenant or of all of them
+fn running_query(tenant_id: Option<&str>) -> String {
+    let status = format!("{} = {}", EXECUTION_STATUS_ATTRIBUTE, quote(visibility_status(&WorkflowStatus::Running)));
+    match tenant_id {
+        Some(tenant_id) => format!("{} = {} AND {}", TENANT_ID_ATTRIBUTE, quote(tenant_id), status),
+        None => status,
+    }
+}
+
+/// Quote a value for a visibility query
+fn quote(value: &str) -> String {
+    format!("'{}'", value.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// The `ExecutionStatus` value visibility queries use for `status`
+fn visibility_status(status: &WorkflowStatus) -> &'static str {
+    match status {
+        WorkflowStatus::Running => "Running",
+        WorkflowStatus::Completed => "Completed",
+        WorkflowStatus::Failed => "Failed",
+        WorkflowStatus::Cancelled => "Canceled",
+        WorkflowStatus::Terminated => "Terminated",
+        WorkflowStatus::ContinuedAsNew => "ContinuedAsNew",
+        WorkflowStatus::TimedOut => "TimedOut",
+    }
+}
+
+/// `WORKFLOW_EXECUTION_STATUS_RUNNING` -> `Running`
+fn parse_api_status(raw: &str) -> Option<WorkflowStatus> {
+    match raw.strip_prefix("WORKFLOW_EXECUTION_STATUS_")? {
+        "RUNNING" => Some(WorkflowStatus::Running),
+        "COMPLETED" => Some(WorkflowStatus::Completed),
+        "FAILED" => Some(WorkflowStatus::Failed),
+        "CANCELED" => Some(WorkflowStatus::Cancelled),
+        "TERMINATED" => Some(WorkflowStatus::Terminated),
+        "CONTINUED_AS_NEW" => Some(WorkflowStatus::ContinuedAsNew),
+        "TIMED_OUT" => Some(WorkflowStatus::TimedOut),
+        _ => None,
+    }
+}
+
+fn parse_time(raw: &str) -> Option<DateTime<Utc>> {
+    DateTime::parse_from_rfc3339(raw).ok().map(|t| t.with_timezone(&Utc))
+}
+
+impl AdxTemporalClient {
+    /// Register the custom search attributes in the client's namespace.
+    /// Attributes that already exist are left as they are.
+    pub async fn register_search_attributes(&self) -> Result<(), TemporalError> {
+        info!(
+            namespace = %self.namespace(),
+            client_id = %self.client_id(),
+            "Registering search attributes with HTTP communication"
+        );
+
+        let url = format!(
+            "http://{}/api/v1/namespaces/{}/search-attributes",
+            self.config().server_address,
+            self.namespace()
+        );
+        let attributes: serde_json::Map<String, serde_json::Value> = CUSTOM_SEARCH_ATTRIBUTES
+            .iter()
+            .map(|(name, index_type)| (name.to_string(), (*index_type).into()))
+            .collect();
+
+        let response = self
+            .http_client()
+            .post(&url)
+            .json(&serde_json::json!({ "searchAttributes": attributes }))
+            .send()
+            .await
+            .map_err(|e| TemporalError::ConnectionError {
+                message: format!("Failed to register search attributes: {}", e),
+            })?;
+
+        if !response.status().is_success() && response.status() != reqwest::StatusCode::CONFLICT {
+            return Err(TemporalError::Generic {
+                message: format!("Temporal returned {} when registering search attributes", response.status()),
+            });
+        }
+
+        debug!(namespace = %self.namespace(), "Search attributes registered");
+        Ok(())
+    }
+
+    /// Start a workflow execution carrying `attributes`
+    pub async fn start_workflow_with_search_attributes<T, R>(
+        &self,
+        workflow_type: &str,
+        workflow_id: String,
+        task_queue: &str,
+        input: T,
+        attributes: &WorkflowSearchAttributes,
+    ) -> Result<WorkflowHandle<R>, TemporalError>
+    where
+        T: serde::Serialize + Send + Sync + 'static,
+        R: serde::de::DeserializeOwned + Send + Sync + 'static,
+    {
+        if attributes.tenant_id.is_empty() {
+            return Err(TemporalError::ConfigurationError {
+                message: format!("Workflow {} has no tenant search attribute", workflow_id),
+            });
+        }
+
+        debug!(
+            workflow_id = %workflow_id,
+            search_attributes = %attributes.indexed_fields(),
+            "Attaching search attributes to workflow execution"
+        );
+
+        self.start_workflow(workflow_type, workflow_id, task_queue, input).await
+    }
+
+    /// One page of executions matching `filter`, newest first
+    pub async fn list_workflow_executions(
+        &self,
+        filter: &WorkflowListFilter,
+        page_size: u32,
+        page_token: Option<&str>,
+    ) -> Result<WorkflowExecutionPage, TemporalError> {
+        // Counting doesn't accept ORDER BY, so only listings sort
+        let query = format!("{} ORDER BY StartTime DESC", filter.query());
+        info!(
+            query = %query,
+            client_id = %self.client_id(),
+            "Listing workflow executions with HTTP communication"
+        );
+
+        let url = format!(
+            "http://{}/api/v1/namespaces/{}/workflows",
+            self.config().server_address,
+            self.namespace()
+        );
+        let page_size = page_size.clamp(1, MAX_LIST_PAGE_SIZE).to_string();
+        let mut params: Vec<(&str, &str)> = vec![("query", &query), ("pageSize", &page_size)];
+        if let Some(token) = page_token {
+            params.push(("nextPageToken", token));
+        }
+
+        let page = self.visibility_request(&url, &params).await?;
+        let executions = page
+            .get("executions")
+            .and_then(|e| e.as_array())
+            .map(|executions| executions.iter().map(WorkflowExecutionSummary::from_json).collect())
+            .transpose()?
+            .unwrap_or_default();
+
+        Ok(WorkflowExecutionPage {
+            executions,
+            next_page_token: page
+                .get("nextPageToken")
+                .and_then(|t| t.as_str())
+                .filter(|t| !t.is_empty())
+                .map(str::to_string),
+        })
+    }
+
+    /// Number of executions matching `filter`
+    pub async fn count_workflow_executions(&self, filter: &WorkflowListFilter) -> Result<u64, TemporalError> {
+        self.count_matching(&filter.query()).await
+    }
+
+    /// Number of running executions, across tenants unless one is given
+    pub async fn count_running_workflows(&self, tenant_id: Option<&str>) -> Result<u64, TemporalError> {
+        self.count_matching(&running_query(tenant_id)).await
+    }
+
+    async fn count_matching(&self, query: &str) -> Result<u64, TemporalError> {
+        let url = format!(
+            "http://{}/api/v1/namespaces/{}/workflow-count",
+            self.config().server_address,
+            self.namespace()
+        );
+
+        let count = self.visibility_request(&url, &[("query", query)]).await?;
+        Ok(count
+            .get("count")
+            .and_then(|c| c.as_u64().or_else(|| c.as_str().and_then(|s| s.parse().ok())))
+            .unwrap_or(0))
+    }
+
+    async fn visibility_request(&self, url: &str, params: &[(&str, &str)]) -> Result<serde_json::Value, TemporalError> {
+        let response = self
+            .http_client()
+            .get(url)
+            .query(params)
+            .send()
+            .await
+            .map_err(|e| TemporalError::ConnectionError {
+                message: format!("Failed to query workflow visibility: {}", e),
+            })?;
+
+        if !response.status().is_success() {
+            return Err(TemporalError::Generic {
+                message: format!("Temporal returned {} for a visibility query", response.status()),
+            });
+        }
+
+        response.json().await.map_err(|e| TemporalError::SerializationError {
+            message: format!("Failed to decode visibility response: {}", e),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_filter_query_quotes_values() {
+        let filter = WorkflowListFilter {
+            tenant_id: "tenant-1".to_string(),
+            status: Some(WorkflowStatus::Cancelled),
+            workflow_type: Some("file_migration_workflow".to_string()),
+            user_id: None,
+            business_key: Some("file:o'brien".to_string()),
+        };
+
+        assert_eq!(
+            filter.query(),
+            "TenantId = 'tenant-1' AND ExecutionStatus = 'Canceled' \
+             AND WorkflowType = 'file_migration_workflow' AND BusinessKeys = 'file:o\\'brien'"
+        );
+    }
+
+    #[test]
+    fn test_running_query() {
+        assert_eq!(running_query(None), "ExecutionStatus = 'Running'");
+        assert_eq!(
+            running_query(Some("tenant-1")),
+            "TenantId = 'tenant-1' AND ExecutionStatus = 'Running'"
+        );
+    }
+
+    #[test]
+    fn test_execution_summary_from_json() {
+        let value = serde_json::json!({
+            "execution": { "workflowId": "wf-1", "runId": "run-1" },
+            "type": { "name": "file_migration_workflow" },
+            "startTime": "2024-05-01T10:00:00Z",
+            "status": "WORKFLOW_EXECUTION_STATUS_RUNNING",
+            "taskQueue": "adx-core-workflows",
+            "searchAttributes": {
+                "indexedFields": {
+                    "TenantId": "tenant-1",
+                    "UserId": "user-1",
+                    "BusinessKeys": ["file:42"]
+                }
+            }
+        });
+
+        let summary = WorkflowExecutionSummary::from_json(&value).unwrap();
+        assert_eq!(summary.status, WorkflowStatus::Running);
+        assert!(summary.close_time.is_none());
+        assert_eq!(
+            summary.search_attributes,
+            WorkflowSearchAttributes::new("tenant-1", "file_migration_workflow")
+                .user(Some("user-1"))
+                .business_key("file:42")
+        );
+    }
+
+    #[test]
+    fn test_indexed_fields_round_trip() {
+        let attributes = WorkflowSearchAttributes::new("tenant-1", "user_onboarding_workflow")
+            .business_key("template:7")
+            .lane(PriorityLane::Batch);
+        let fields = attributes.indexed_fields();
+
+        assert!(fields.get(USER_ID_ATTRIBUTE).is_none());
+        assert_eq!(
+            WorkflowSearchAttributes::from_indexed_fields("user_onboarding_workflow", &fields),
+            attributes
+        );
+    }
+}
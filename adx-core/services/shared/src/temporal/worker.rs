@@ -4,7 +4,8 @@ use tokio::sync::RwLock;
 use tracing::{info, error, debug};
 use async_trait::async_trait;
 
-use crate::temporal::{TemporalConfig, TemporalError};
+use crate::telemetry;
+use crate::temporal::{PriorityLane, TemporalConfig, TemporalError};
 use crate::temporal::sdk_client::{TemporalSDKClient, TemporalWorker, WorkerConfig};
 
 /// Worker instance placeholder for SDK Core integration
@@ -159,10 +160,13 @@ impl AdxTemporalWorkerManager {
         
         let mut workers = self.workers.write().await;
         
-        // Create a worker for each task queue using the SDK client
-        for task_queue in &self.task_queues {
+        // Create a worker for each task queue using the SDK client. The
+        // interactive pool comes up first so user-facing work is served as
+        // soon as possible.
+        for (lane, task_queue) in self.pools() {
             info!(
                 task_queue = task_queue,
+                pool = lane.as_str(),
                 worker_identity = %self.worker_identity,
                 "Creating SDK worker for task queue"
             );
@@ -233,6 +237,63 @@ impl AdxTemporalWorkerManager {
         &self.task_queues
     }
     
+    /// Task queues of the pool serving `lane`
+    pub fn pool_task_queues(&self, lane: PriorityLane) -> Vec<String> {
+        self.pools()
+            .filter(|(pool, _)| *pool == lane)
+            .map(|(_, task_queue)| task_queue.clone())
+            .collect()
+    }
+    
+    /// Task queues with the pool they belong to, interactive pool first
+    fn pools(&self) -> impl Iterator<Item = (PriorityLane, &String)> {
+        let interactive = self
+            .task_queues
+            .iter()
+            .filter(|queue| self.config.worker.lane_of(queue) == PriorityLane::Interactive)
+            .map(|queue| (PriorityLane::Interactive, queue));
+        let batch = self
+            .task_queues
+            .iter()
+            .filter(|queue| self.config.worker.lane_of(queue) == PriorityLane::Batch)
+            .map(|queue| (PriorityLane::Batch, queue));
+        interactive.chain(batch)
+    }
+    
+    /// Run a registered workflow, continuing the trace it was started from
+    pub async fn run_workflow(
+        &self,
+        workflow_type: &str,
+        workflow_id: &str,
+        headers: &HashMap<String, String>,
+        input: Vec<u8>,
+    ) -> Result<Vec<u8>, WorkflowExecutionError> {
+        let registry = self.workflow_registry.read().await;
+        let workflow = registry.get(workflow_type).ok_or_else(|| WorkflowExecutionError::ExecutionFailed {
+            message: format!("Workflow type {} is not registered", workflow_type),
+        })?;
+        
+        let span = telemetry::workflow_span(workflow_type, workflow_id, headers);
+        span.in_scope(|| workflow.execute(input))
+    }
+    
+    /// Run a registered activity, continuing the trace of the workflow that scheduled it
+    pub async fn run_activity(
+        &self,
+        activity_type: &str,
+        activity_id: &str,
+        headers: &HashMap<String, String>,
+        input: Vec<u8>,
+    ) -> Result<Vec<u8>, ActivityExecutionError> {
+        let registry = self.activity_registry.read().await;
+        let activity = registry.get(activity_type).ok_or_else(|| ActivityExecutionError::NonRetryable {
+            message: format!("Activity type {} is not registered", activity_type),
+        })?;
+        
+        let span = telemetry::activity_span(activity_type, activity_id, headers);
+        span.in_scope(|| activity.execute(input))
+    }
+    
     /// Get registered workflow count
     pub async fn workflow_count(&self) -> usize {
         self.workflow_registry.read().await.len()
@@ -247,7 +308,7 @@ impl AdxTemporalWorkerManager {
 #[cfg(test)]
 mod tests {
     use super::*;
-    use crate::temporal::TemporalConfig;
+    use crate::temporal::{TaskQueueLimits, TemporalConfig};
     
     // Mock workflow function for testing
     struct TestWorkflow;
@@ -309,4 +370,71 @@ mod tests {
         // Check activity count
         assert_eq!(worker_manager.activity_count().await, 1);
     }
+    
+    #[tokio::test]
+    async fn test_run_registered_functions() {
+        let config = TemporalConfig::development();
+        let task_queues = vec!["test-queue".to_string()];
+        let worker_manager = AdxTemporalWorkerManager::new(config, task_queues).await.unwrap();
+        worker_manager.register_workflow("test-workflow", TestWorkflow).await.unwrap();
+        worker_manager.register_activity("test-activity", TestActivity).await.unwrap();
+        
+        let headers = HashMap::from([(
+            "traceparent".to_string(),
+            "00-4bf92f3577b34da6a3ce929d0e0e4736-00f067aa0ba902b7-01".to_string(),
+        )]);
+        
+        let output = worker_manager
+            .run_workflow("test-workflow", "wf-1", &headers, b"input".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(output, b"input");
+        
+        let output = worker_manager
+            .run_activity("test-activity", "act-1", &headers, b"input".to_vec())
+            .await
+            .unwrap();
+        assert_eq!(output, b"input");
+        
+        let missing = worker_manager
+            .run_activity("missing-activity", "act-2", &HashMap::new(), Vec::new())
+            .await;
+        assert!(matches!(missing, Err(ActivityExecutionError::NonRetryable { .. })));
+    }
+    
+    #[test]
+    fn test_task_queue_limits() {
+        let mut config = TemporalConfig::development().worker;
+        config.max_concurrent_workflow_tasks = 100;
+        config.max_concurrent_activity_tasks = 200;
+        config.task_queue_limits.insert(
+            "batch-queue".to_string(),
+            TaskQueueLimits {
+                max_concurrent_workflow_tasks: 25,
+                max_concurrent_activity_tasks: 500,
+            },
+        );
+        
+        let batch = config.limits_for("batch-queue");
+        assert_eq!(batch.max_concurrent_workflow_tasks, 25);
+        assert_eq!(batch.max_concurrent_activity_tasks, 200);
+        
+        let interactive = config.limits_for("interactive-queue");
+        assert_eq!(interactive.max_concurrent_workflow_tasks, 100);
+        assert_eq!(interactive.max_concurrent_activity_tasks, 200);
+    }
+    
+    #[tokio::test]
+    async fn test_worker_pools() {
+        let mut config = TemporalConfig::development();
+        config
+            .worker
+            .task_queue_lanes
+            .insert("import-queue".to_string(), PriorityLane::Batch);
+        let task_queues = vec!["import-queue".to_string(), "tenant-queue".to_string()];
+        let worker_manager = AdxTemporalWorkerManager::new(config, task_queues).await.unwrap();
+        
+        assert_eq!(worker_manager.pool_task_queues(PriorityLane::Interactive), vec!["tenant-queue"]);
+        assert_eq!(worker_manager.pool_task_queues(PriorityLane::Batch), vec!["import-queue"]);
+    }
 }
\ No newline at end of file
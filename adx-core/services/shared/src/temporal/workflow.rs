@@ -1,8 +1,14 @@
+use futures::stream::{self, StreamExt};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
+use std::future::Future;
+use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
 use std::time::Duration;
 use uuid::Uuid;
 
+use crate::telemetry::current_trace_headers;
 use crate::temporal::{WorkflowVersion, TemporalError, WorkflowError};
 
 /// Workflow execution context for ADX Core
@@ -226,7 +232,7 @@ pub struct WorkflowRetryPolicy {
 }
 
 /// Workflow priority
-#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
 pub enum WorkflowPriority {
     Low = 1,
     Normal = 2,
@@ -291,6 +297,10 @@ pub struct WorkflowExecutionOptions {
     
     /// Cron schedule (for scheduled workflows)
     pub cron_schedule: Option<String>,
+    
+    /// Temporal header fields; carries the caller's trace context
+    #[serde(default)]
+    pub headers: HashMap<String, String>,
 }
 
 impl Default for WorkflowExecutionOptions {
@@ -305,6 +315,7 @@ impl Default for WorkflowExecutionOptions {
             search_attributes: HashMap::new(),
             memo: HashMap::new(),
             cron_schedule: None,
+            headers: HashMap::new(),
         }
     }
 }
@@ -483,6 +494,9 @@ where
             message: "Tenant context is required".to_string(),
         })?;
         
+        let mut options = self.options;
+        options.headers.extend(current_trace_headers());
+        
         Ok(WorkflowExecutionRequest {
             workflow_type: self.workflow_type,
             workflow_id: self.workflow_id,
@@ -491,9 +505,348 @@ where
             input,
             user_context,
             tenant_context,
-            options: self.options,
+            options,
+        })
+    }
+}
+
+/// Boxed future returned by a saga compensation
+pub type CompensationFuture<'a, E> = Pin<Box<dyn Future<Output = Result<(), E>> + Send + 'a>>;
+
+type Compensation<'a, E> = Box<dyn FnOnce() -> CompensationFuture<'a, E> + Send + 'a>;
+
+/// Builder for a [`Saga`]
+pub struct SagaBuilder {
+    name: String,
+    continue_on_compensation_error: bool,
+}
+
+impl SagaBuilder {
+    pub fn new(name: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            continue_on_compensation_error: true,
+        }
+    }
+
+    /// Keep compensating earlier steps when one compensation fails. On by
+    /// default, so a single failed undo doesn't strand the remaining ones.
+    pub fn continue_on_compensation_error(mut self, continue_on_error: bool) -> Self {
+        self.continue_on_compensation_error = continue_on_error;
+        self
+    }
+
+    pub fn build<'a, E>(self) -> Saga<'a, E> {
+        Saga {
+            name: self.name,
+            continue_on_compensation_error: self.continue_on_compensation_error,
+            executed_steps: Vec::new(),
+            compensations: Vec::new(),
+        }
+    }
+}
+
+/// Tracks the steps a workflow has executed and how to undo them.
+///
+/// Register a compensation once its step has succeeded, either with
+/// [`Saga::add_compensation`] or by running the step through [`Saga::run`].
+/// When a later step fails, [`Saga::compensate`] undoes the completed steps
+/// in reverse order.
+pub struct Saga<'a, E> {
+    name: String,
+    continue_on_compensation_error: bool,
+    executed_steps: Vec<String>,
+    compensations: Vec<(String, Compensation<'a, E>)>,
+}
+
+/// What happened while compensating a saga
+#[derive(Debug)]
+pub struct SagaCompensation<E> {
+    /// Steps undone, in the order their compensations ran
+    pub compensated: Vec<String>,
+    /// Steps whose compensation failed, with the error
+    pub failed: Vec<(String, E)>,
+    /// Steps not compensated because an earlier compensation failed
+    pub skipped: Vec<String>,
+}
+
+impl<E> SagaCompensation<E> {
+    /// Whether every completed step was undone
+    pub fn is_complete(&self) -> bool {
+        self.failed.is_empty() && self.skipped.is_empty()
+    }
+}
+
+impl<'a, E> Saga<'a, E> {
+    /// A saga with default options
+    pub fn new(name: &str) -> Self {
+        SagaBuilder::new(name).build()
+    }
+
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
+    /// Steps that completed, in execution order
+    pub fn executed_steps(&self) -> &[String] {
+        &self.executed_steps
+    }
+
+    /// Record a completed step and how to undo it
+    pub fn add_compensation<F, Fut>(&mut self, step: &str, compensation: F)
+    where
+        F: FnOnce() -> Fut + Send + 'a,
+        Fut: Future<Output = Result<(), E>> + Send + 'a,
+    {
+        self.executed_steps.push(step.to_string());
+        self.compensations.push((
+            step.to_string(),
+            Box::new(move || Box::pin(compensation()) as CompensationFuture<'a, E>),
+        ));
+    }
+
+    /// Record a completed step that needs no undo, such as a read or a
+    /// validation
+    pub fn add_step(&mut self, step: &str) {
+        self.executed_steps.push(step.to_string());
+    }
+
+    /// Run a step and, when it succeeds, register the compensation built
+    /// from its output. A failed step registers nothing.
+    pub async fn run<T, Act, C, CFut>(
+        &mut self,
+        step: &str,
+        action: Act,
+        compensation: C,
+    ) -> Result<T, E>
+    where
+        Act: Future<Output = Result<T, E>>,
+        C: FnOnce(&T) -> CFut + Send + 'a,
+        CFut: Future<Output = Result<(), E>> + Send + 'a,
+    {
+        let output = action.await?;
+        let undo = compensation(&output);
+        self.add_compensation(step, move || undo);
+        Ok(output)
+    }
+
+    /// Past the point of no return: drop the registered compensations so a
+    /// later failure leaves the completed steps in place
+    pub fn commit(&mut self) {
+        self.compensations.clear();
+    }
+
+    /// Undo the completed steps, most recent first
+    pub async fn compensate(self) -> SagaCompensation<E> {
+        let mut report = SagaCompensation {
+            compensated: Vec::new(),
+            failed: Vec::new(),
+            skipped: Vec::new(),
+        };
+
+        let mut pending = self.compensations.into_iter().rev();
+        for (step, compensation) in pending.by_ref() {
+            match compensation().await {
+                Ok(()) => report.compensated.push(step),
+                Err(error) => {
+                    report.failed.push((step, error));
+                    if !self.continue_on_compensation_error {
+                        break;
+                    }
+                }
+            }
+        }
+        report.skipped = pending.map(|(step, _)| step).collect();
+
+        report
+    }
+}
+
+/// Records the order compensations run in, for unit tests of saga-based
+/// workflows
+#[derive(Debug, Clone, Default)]
+pub struct CompensationRecorder {
+    entries: Arc<Mutex<Vec<String>>>,
+}
+
+impl CompensationRecorder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A compensation that records `step` and succeeds
+    pub fn compensation<E: Send + 'static>(
+        &self,
+        step: &str,
+    ) -> impl FnOnce() -> CompensationFuture<'static, E> + Send + 'static {
+        let entries = self.entries.clone();
+        let step = step.to_string();
+        move || {
+            Box::pin(async move {
+                entries.lock().unwrap().push(step);
+                Ok(())
+            })
+        }
+    }
+
+    /// A compensation that records `step` and then fails with `error`
+    pub fn failing_compensation<E: Send + 'static>(
+        &self,
+        step: &str,
+        error: E,
+    ) -> impl FnOnce() -> CompensationFuture<'static, E> + Send + 'static {
+        let entries = self.entries.clone();
+        let step = step.to_string();
+        move || {
+            Box::pin(async move {
+                entries.lock().unwrap().push(step);
+                Err(error)
+            })
+        }
+    }
+
+    /// Compensations that ran, in order
+    pub fn recorded(&self) -> Vec<String> {
+        self.entries.lock().unwrap().clone()
+    }
+
+    /// Panic unless the compensations ran in exactly this order
+    pub fn assert_order(&self, expected: &[&str]) {
+        assert_eq!(self.recorded(), expected, "compensations ran in an unexpected order");
+    }
+
+    /// Panic unless the compensations undid `executed` in reverse
+    pub fn assert_reverse_of(&self, executed: &[&str]) {
+        let reversed: Vec<&str> = executed.iter().rev().copied().collect();
+        self.assert_order(&reversed);
+    }
+}
+
+/// Outcome of one child in a fan-out
+#[derive(Debug, Clone, PartialEq)]
+pub enum ChildOutcome<T, E> {
+    Completed(T),
+    Failed(E),
+    /// Not started because an earlier child failed under fail-fast
+    Skipped,
+}
+
+/// Runs one child per input with a bounded number in flight
+#[derive(Debug, Clone)]
+pub struct FanOut {
+    parallelism: usize,
+    fail_fast: bool,
+}
+
+impl FanOut {
+    pub fn new(parallelism: usize) -> Self {
+        Self {
+            parallelism: parallelism.max(1),
+            fail_fast: false,
+        }
+    }
+
+    /// Stop starting children once one has failed; children already in
+    /// flight still finish
+    pub fn fail_fast(mut self, fail_fast: bool) -> Self {
+        self.fail_fast = fail_fast;
+        self
+    }
+
+    /// Run `child` for every input, at most `parallelism` at a time, and
+    /// collect the outcomes in input order
+    pub async fn run<I, T, E, F, Fut>(&self, inputs: Vec<I>, child: F) -> FanIn<T, E>
+    where
+        F: Fn(usize, I) -> Fut,
+        Fut: Future<Output = Result<T, E>>,
+    {
+        let stopped = AtomicBool::new(false);
+        let mut outcomes: Vec<Option<ChildOutcome<T, E>>> = inputs.iter().map(|_| None).collect();
+
+        let settled = stream::iter(inputs.into_iter().enumerate())
+            .map(|(index, input)| {
+                let stopped = &stopped;
+                let child = &child;
+                async move {
+                    if stopped.load(Ordering::SeqCst) {
+                        return (index, ChildOutcome::Skipped);
+                    }
+                    match child(index, input).await {
+                        Ok(output) => (index, ChildOutcome::Completed(output)),
+                        Err(error) => {
+                            if self.fail_fast {
+                                stopped.store(true, Ordering::SeqCst);
+                            }
+                            (index, ChildOutcome::Failed(error))
+                        }
+                    }
+                }
+            })
+            .buffer_unordered(self.parallelism)
+            .collect::<Vec<_>>()
+            .await;
+
+        for (index, outcome) in settled {
+            outcomes[index] = Some(outcome);
+        }
+
+        FanIn {
+            outcomes: outcomes
+                .into_iter()
+                .map(|outcome| outcome.unwrap_or(ChildOutcome::Skipped))
+                .collect(),
+        }
+    }
+}
+
+/// Outcomes of a fan-out, in input order
+#[derive(Debug, Clone)]
+pub struct FanIn<T, E> {
+    pub outcomes: Vec<ChildOutcome<T, E>>,
+}
+
+impl<T, E> FanIn<T, E> {
+    pub fn completed(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.outcomes.iter().enumerate().filter_map(|(index, outcome)| match outcome {
+            ChildOutcome::Completed(output) => Some((index, output)),
+            _ => None,
+        })
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = (usize, &E)> {
+        self.outcomes.iter().enumerate().filter_map(|(index, outcome)| match outcome {
+            ChildOutcome::Failed(error) => Some((index, error)),
+            _ => None,
         })
     }
+
+    pub fn skipped_count(&self) -> usize {
+        self.outcomes
+            .iter()
+            .filter(|outcome| matches!(outcome, ChildOutcome::Skipped))
+            .count()
+    }
+
+    /// Whether every child completed
+    pub fn is_success(&self) -> bool {
+        self.outcomes
+            .iter()
+            .all(|outcome| matches!(outcome, ChildOutcome::Completed(_)))
+    }
+
+    /// All outputs in input order, or the first failure
+    pub fn into_result(self) -> Result<Vec<T>, E> {
+        let mut outputs = Vec::with_capacity(self.outcomes.len());
+        for outcome in self.outcomes {
+            match outcome {
+                ChildOutcome::Completed(output) => outputs.push(output),
+                ChildOutcome::Failed(error) => return Err(error),
+                // Skipped children always follow the failure that stopped them
+                ChildOutcome::Skipped => {}
+            }
+        }
+        Ok(outputs)
+    }
 }
 
 /// Utility functions for workflow management
@@ -569,6 +922,7 @@ pub mod utils {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use std::sync::atomic::AtomicUsize;
     
     #[test]
     fn test_workflow_builder() {
@@ -661,4 +1015,131 @@ mod tests {
         assert_eq!(attributes.get("UserId"), Some(&serde_json::Value::String("user123".to_string())));
         assert_eq!(attributes.get("SubscriptionTier"), Some(&serde_json::Value::String("Enterprise".to_string())));
     }
+
+    #[tokio::test]
+    async fn test_saga_compensates_in_reverse_order() {
+        let recorder = CompensationRecorder::new();
+        let mut saga: Saga<'_, String> = Saga::new("create_tenant");
+
+        saga.add_compensation("create_database", recorder.compensation("create_database"));
+        saga.add_step("validate_quotas");
+        let tenant_id = saga
+            .run(
+                "create_tenant_record",
+                async { Ok::<_, String>("tenant123".to_string()) },
+                |_| recorder.compensation("create_tenant_record")(),
+            )
+            .await
+            .unwrap();
+        assert_eq!(tenant_id, "tenant123");
+
+        let failed = saga
+            .run(
+                "provision_storage",
+                async { Err::<(), _>("storage unavailable".to_string()) },
+                |_| recorder.compensation("provision_storage")(),
+            )
+            .await;
+        assert!(failed.is_err());
+        assert_eq!(
+            saga.executed_steps(),
+            ["create_database", "validate_quotas", "create_tenant_record"]
+        );
+
+        let report = saga.compensate().await;
+        assert!(report.is_complete());
+        recorder.assert_reverse_of(&["create_database", "create_tenant_record"]);
+    }
+
+    #[tokio::test]
+    async fn test_saga_compensation_failures() {
+        let recorder = CompensationRecorder::new();
+        let mut saga: Saga<'_, String> = Saga::new("install_module");
+        saga.add_compensation("download", recorder.compensation("download"));
+        saga.add_compensation("extract", recorder.failing_compensation("extract", "locked".to_string()));
+        saga.add_compensation("register", recorder.compensation("register"));
+
+        let report = saga.compensate().await;
+        recorder.assert_order(&["register", "extract", "download"]);
+        assert_eq!(report.compensated, vec!["register", "download"]);
+        assert_eq!(report.failed, vec![("extract".to_string(), "locked".to_string())]);
+
+        let recorder = CompensationRecorder::new();
+        let mut saga: Saga<'_, String> = SagaBuilder::new("install_module")
+            .continue_on_compensation_error(false)
+            .build();
+        saga.add_compensation("download", recorder.compensation("download"));
+        saga.add_compensation("extract", recorder.failing_compensation("extract", "locked".to_string()));
+        saga.add_compensation("register", recorder.compensation("register"));
+
+        let report = saga.compensate().await;
+        recorder.assert_order(&["register", "extract"]);
+        assert_eq!(report.skipped, vec!["download"]);
+        assert!(!report.is_complete());
+    }
+
+    #[tokio::test]
+    async fn test_saga_commit_drops_compensations() {
+        let recorder = CompensationRecorder::new();
+        let mut saga: Saga<'_, String> = Saga::new("migrate_region");
+        saga.add_compensation("copy_data", recorder.compensation("copy_data"));
+        saga.commit();
+        saga.add_compensation("unfreeze_writes", recorder.compensation("unfreeze_writes"));
+
+        saga.compensate().await;
+        recorder.assert_order(&["unfreeze_writes"]);
+    }
+    #[tokio::test]
+    async fn test_fan_out_bounds_parallelism_and_keeps_input_order() {
+        let in_flight = Arc::new(AtomicUsize::new(0));
+        let peak = Arc::new(AtomicUsize::new(0));
+
+        let fan_in = FanOut::new(2)
+            .run(vec![30u64, 10, 20, 5], |index, delay| {
+                let in_flight = in_flight.clone();
+                let peak = peak.clone();
+                async move {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    peak.fetch_max(now, Ordering::SeqCst);
+                    tokio::time::sleep(Duration::from_millis(delay)).await;
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    if index == 2 {
+                        Err(format!("child {} failed", index))
+                    } else {
+                        Ok(delay * 2)
+                    }
+                }
+            })
+            .await;
+
+        assert!(peak.load(Ordering::SeqCst) <= 2);
+        assert_eq!(fan_in.completed().collect::<Vec<_>>(), vec![(0, &60), (1, &20), (3, &10)]);
+        assert_eq!(fan_in.failed().map(|(index, _)| index).collect::<Vec<_>>(), vec![2]);
+        assert!(!fan_in.is_success());
+        assert_eq!(fan_in.into_result(), Err("child 2 failed".to_string()));
+    }
+
+    #[tokio::test]
+    async fn test_fan_out_fail_fast_skips_unstarted_children() {
+        let fan_in = FanOut::new(1)
+            .fail_fast(true)
+            .run(vec!["a", "b", "c"], |_, input| async move {
+                if input == "b" {
+                    Err("b failed")
+                } else {
+                    Ok(input.to_uppercase())
+                }
+            })
+            .await;
+
+        assert_eq!(
+            fan_in.outcomes,
+            vec![
+                ChildOutcome::Completed("A".to_string()),
+                ChildOutcome::Failed("b failed"),
+                ChildOutcome::Skipped,
+            ]
+        );
+        assert_eq!(fan_in.skipped_count(), 1);
+    }
 }
\ No newline at end of file
@@ -6,15 +6,50 @@ mod tests {
     use chrono::Utc;
     use rust_decimal::Decimal;
     use crate::services::TenantService;
-    use crate::repositories_simple::{SimpleTenantRepository, SimpleTenantMembershipRepository};
+    use crate::repositories_simple::{SimpleTenantRepository, SimpleTenantMembershipRepository, SimpleTenantTemplateRepository, SimpleFeatureFlagRepository, SimpleTenantConfigRepository, SimpleTenantArchiveRepository, SimpleTenantDeletionRepository, SimpleLifecycleEventRepository};
     use crate::activities::*;
-    use adx_shared::types::{SubscriptionTier, TenantQuotas};
+    use crate::deletion::{IssueDestructionCertificateRequest, TenantPurgeClient};
+    use adx_shared::types::{SubscriptionTier, TenantId, TenantQuotas};
+
+    /// Records purges and certificates instead of calling other services
+    #[derive(Default)]
+    struct RecordingPurgeClient {
+        failing_service: Option<&'static str>,
+        purged: std::sync::Mutex<Vec<String>>,
+        certificates: std::sync::Mutex<Vec<IssueDestructionCertificateRequest>>,
+    }
+
+    #[async_trait::async_trait]
+    impl TenantPurgeClient for RecordingPurgeClient {
+        async fn purge_service(&self, service: &str, _tenant_id: &TenantId) -> anyhow::Result<u64> {
+            if self.failing_service == Some(service) {
+                return Err(anyhow::anyhow!("{} is unreachable", service));
+            }
+            self.purged.lock().unwrap().push(service.to_string());
+            Ok(3)
+        }
+
+        async fn issue_destruction_certificate(&self, request: &IssueDestructionCertificateRequest) -> anyhow::Result<()> {
+            self.certificates.lock().unwrap().push(request.clone());
+            Ok(())
+        }
+    }
 
     fn create_test_activities() -> TenantActivitiesImpl {
+        create_test_activities_with(Arc::new(RecordingPurgeClient::default()))
+    }
+
+    fn create_test_activities_with(purge_client: Arc<RecordingPurgeClient>) -> TenantActivitiesImpl {
         let tenant_repo = Arc::new(SimpleTenantRepository::new());
         let membership_repo = Arc::new(SimpleTenantMembershipRepository::new());
-        let tenant_service = Arc::new(TenantService::new(tenant_repo, membership_repo));
-        TenantActivitiesImpl::new(tenant_service)
+        let template_repo = Arc::new(SimpleTenantTemplateRepository::new());
+        let flag_repo = Arc::new(SimpleFeatureFlagRepository::new());
+        let config_repo = Arc::new(SimpleTenantConfigRepository::new());
+        let archive_repo = Arc::new(SimpleTenantArchiveRepository::new());
+        let deletion_repo = Arc::new(SimpleTenantDeletionRepository::new());
+        let event_repo = Arc::new(SimpleLifecycleEventRepository::new());
+        let tenant_service = Arc::new(TenantService::new(tenant_repo, membership_repo, template_repo, flag_repo, config_repo, archive_repo, deletion_repo, event_repo));
+        TenantActivitiesImpl::new(tenant_service, purge_client)
     }
 
     #[tokio::test]
@@ -113,6 +148,8 @@ mod tests {
             isolation_level: None,
             features: None,
             settings: None,
+            quotas: None,
+            template: None,
         };
         let tenant = activities.tenant_service().create_tenant(create_request).await.unwrap();
         
@@ -149,6 +186,8 @@ mod tests {
             isolation_level: None,
             features: None,
             settings: None,
+            quotas: None,
+            template: None,
         };
         let tenant = activities.tenant_service().create_tenant(create_request).await.unwrap();
         
@@ -251,4 +290,184 @@ mod tests {
         assert_eq!(result.new_configuration.tier, SubscriptionTier::Enterprise);
         assert!(result.rollback_info.is_some());
     }
-}
\ No newline at end of file
+
+    #[tokio::test]
+    async fn test_region_migration_freeze_and_compensation() {
+        let activities = create_test_activities();
+
+        let create_request = crate::models::CreateTenantRequest {
+            name: "EU Tenant".to_string(),
+            admin_email: "admin@eu.test".to_string(),
+            subscription_tier: Some(SubscriptionTier::Enterprise),
+            isolation_level: None,
+            features: None,
+            settings: None,
+            quotas: None,
+            template: None,
+        };
+        let tenant = activities.tenant_service().create_tenant(create_request).await.unwrap();
+
+        let frozen = activities.freeze_tenant_writes(&tenant.id).await.unwrap();
+        assert_eq!(frozen.status, crate::models::TenantStatus::ReadOnly);
+
+        let rename = activities.tenant_service().update_tenant(&tenant.id, crate::models::UpdateTenantRequest {
+            name: Some("Renamed".to_string()),
+            subscription_tier: None,
+            quotas: None,
+            features: None,
+            settings: None,
+            status: None,
+        }).await;
+        assert!(rename.unwrap_err().to_string().contains("read-only"));
+
+        activities.switch_tenant_region(&tenant.id, "eu-west-1").await.unwrap();
+
+        for step in [crate::region_migration::RegionMigrationStep::SwitchRegion, crate::region_migration::RegionMigrationStep::FreezeWrites] {
+            activities.compensate_region_migration_step(CompensateRegionMigrationStepRequest {
+                tenant_id: tenant.id.clone(),
+                step,
+                source_region: "us-east-1".to_string(),
+                target_region: "eu-west-1".to_string(),
+            }).await.unwrap();
+        }
+
+        let restored = activities.tenant_service().get_tenant(&tenant.id).await.unwrap().unwrap();
+        assert_eq!(restored.status, crate::models::TenantStatus::Active);
+        assert_eq!(restored.region.as_deref(), Some("us-east-1"));
+    }
+
+    #[tokio::test]
+    async fn test_lifecycle_events_are_logged_in_order() {
+        let activities = create_test_activities();
+        let service = activities.tenant_service();
+        let mut stream = service.subscribe_lifecycle_events();
+
+        let tenant = service.create_tenant(crate::models::CreateTenantRequest {
+            name: "Evented Tenant".to_string(),
+            admin_email: "admin@evented.test".to_string(),
+            subscription_tier: Some(SubscriptionTier::Free),
+            isolation_level: None,
+            features: None,
+            settings: None,
+            quotas: None,
+            template: None,
+        }).await.unwrap();
+        service.apply_tier_defaults(&tenant.id, SubscriptionTier::Enterprise).await.unwrap();
+        service.suspend_tenant(&tenant.id).await.unwrap();
+        service.delete_tenant(&tenant.id).await.unwrap();
+
+        let events = service.list_lifecycle_events(0, Some(&tenant.id), 100).await.unwrap();
+        let types: Vec<&str> = events.iter().map(|e| e.event_type.as_str()).collect();
+        assert_eq!(types, vec!["tenant.created", "tenant.upgraded", "tenant.suspended", "tenant.terminated"]);
+        assert_eq!(events.iter().map(|e| e.sequence).collect::<Vec<_>>(), vec![1, 2, 3, 4]);
+
+        let later = service.list_lifecycle_events(2, None, 100).await.unwrap();
+        assert_eq!(later.len(), 2);
+
+        assert_eq!(stream.recv().await.unwrap().event_id, events[0].event_id);
+    }
+
+    #[tokio::test]
+    async fn test_staged_deletion_purges_after_retention() {
+        let purge_client = Arc::new(RecordingPurgeClient::default());
+        let activities = create_test_activities_with(purge_client.clone());
+        let service = activities.tenant_service();
+
+        let tenant = service.create_tenant(crate::models::CreateTenantRequest {
+            name: "Doomed Tenant".to_string(),
+            admin_email: "admin@doomed.test".to_string(),
+            subscription_tier: Some(SubscriptionTier::Professional),
+            isolation_level: None,
+            features: None,
+            settings: None,
+            quotas: None,
+            template: None,
+        }).await.unwrap();
+
+        // Cancelled during retention: the tenant comes back untouched
+        let record = activities.soft_delete_tenant(SoftDeleteTenantRequest {
+            tenant_id: tenant.id.clone(),
+            requested_by: "admin-1".to_string(),
+            reason: None,
+            retention_days: 30,
+        }).await.unwrap();
+        assert!(activities.begin_tenant_purge(&tenant.id).await.is_err());
+        assert!(record.can_cancel());
+        service.cancel_tenant_deletion(&tenant.id).await.unwrap();
+        assert_eq!(service.get_tenant(&tenant.id).await.unwrap().unwrap().status, crate::models::TenantStatus::Active);
+
+        // No retention: purge every service and close out with a certificate
+        activities.soft_delete_tenant(SoftDeleteTenantRequest {
+            tenant_id: tenant.id.clone(),
+            requested_by: "admin-1".to_string(),
+            reason: Some("Customer request".to_string()),
+            retention_days: 0,
+        }).await.unwrap();
+        let record = activities.begin_tenant_purge(&tenant.id).await.unwrap();
+        assert!(service.cancel_tenant_deletion(&tenant.id).await.is_err());
+
+        for purge_service in record.pending_purges() {
+            activities.purge_tenant_service_data(PurgeTenantServiceDataRequest {
+                tenant_id: tenant.id.clone(),
+                service: purge_service.to_string(),
+            }).await.unwrap();
+        }
+        assert!(service.get_tenant(&tenant.id).await.unwrap().is_none());
+
+        let record = activities.get_tenant_deletion(&tenant.id).await.unwrap();
+        assert!(record.pending_purges().is_empty());
+        assert_eq!(purge_client.purged.lock().unwrap().len(), crate::deletion::PURGE_TARGET_SERVICES.len() - 1);
+        assert!(record.purged_services.iter().all(|p| p.service == "tenant-service" || p.records_deleted == 3));
+
+        let certificate_id = activities.request_destruction_certificate(record.clone()).await.unwrap();
+        assert_eq!(activities.request_destruction_certificate(record).await.unwrap(), certificate_id);
+        {
+            let certificates = purge_client.certificates.lock().unwrap();
+            assert_eq!(certificates.len(), 2);
+            assert!(certificates.iter().all(|c| c.certificate_id == certificate_id));
+            assert_eq!(certificates[0].purged_services.len(), crate::deletion::PURGE_TARGET_SERVICES.len());
+        }
+
+        let record = activities.complete_tenant_purge(&tenant.id, certificate_id.clone()).await.unwrap();
+        assert_eq!(record.stage, crate::models::TenantDeletionStage::Purged);
+        assert_eq!(record.certificate_id, Some(certificate_id));
+    }
+
+    #[tokio::test]
+    async fn test_failed_service_purge_is_not_recorded() {
+        let purge_client = Arc::new(RecordingPurgeClient {
+            failing_service: Some("file-service"),
+            ..Default::default()
+        });
+        let activities = create_test_activities_with(purge_client.clone());
+        let service = activities.tenant_service();
+
+        let tenant = service.create_tenant(crate::models::CreateTenantRequest {
+            name: "Stuck Tenant".to_string(),
+            admin_email: "admin@stuck.test".to_string(),
+            subscription_tier: Some(SubscriptionTier::Professional),
+            isolation_level: None,
+            features: None,
+            settings: None,
+            quotas: None,
+            template: None,
+        }).await.unwrap();
+        activities.soft_delete_tenant(SoftDeleteTenantRequest {
+            tenant_id: tenant.id.clone(),
+            requested_by: "admin-1".to_string(),
+            reason: None,
+            retention_days: 0,
+        }).await.unwrap();
+        activities.begin_tenant_purge(&tenant.id).await.unwrap();
+
+        let result = activities.purge_tenant_service_data(PurgeTenantServiceDataRequest {
+            tenant_id: tenant.id.clone(),
+            service: "file-service".to_string(),
+        }).await;
+        assert!(result.is_err());
+
+        let record = activities.get_tenant_deletion(&tenant.id).await.unwrap();
+        assert!(record.pending_purges().contains(&"file-service"));
+        assert!(purge_client.purged.lock().unwrap().is_empty());
+    }
+}
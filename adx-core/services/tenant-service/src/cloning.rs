@@ -0,0 +1,96 @@
+use sha2::{Digest, Sha256};
+
+use crate::export::TenantArchiveData;
+use crate::models::*;
+
+// Helpers for cloning a tenant into a sandbox. Clones must never reach
+// production endpoints or expose customer PII, so outbound integrations are
+// always stripped and personal data is replaced with salted pseudonyms.
+
+/// Domain used for pseudonymized email addresses; reserved, never routable
+pub const ANONYMIZED_EMAIL_DOMAIN: &str = "example.invalid";
+
+fn pseudonym(value: &str, salt: &str) -> String {
+    let digest = Sha256::digest(format!("{}:{}", salt, value).as_bytes());
+    format!("{:x}", digest)[..12].to_string()
+}
+
+/// Replace an email with a stable pseudonym; the same input and salt always
+/// map to the same address so references stay consistent within a clone
+pub fn anonymize_email(email: &str, salt: &str) -> String {
+    format!("user-{}@{}", pseudonym(&email.to_lowercase(), salt), ANONYMIZED_EMAIL_DOMAIN)
+}
+
+/// Replace a filename with a pseudonym, keeping the extension
+pub fn anonymize_filename(filename: &str, salt: &str) -> String {
+    match filename.rsplit_once('.') {
+        Some((_, extension)) if !extension.is_empty() => {
+            format!("file-{}.{}", pseudonym(filename, salt), extension.to_lowercase())
+        }
+        _ => format!("file-{}", pseudonym(filename, salt)),
+    }
+}
+
+/// Settings safe for a sandbox copy: no custom domain and no outbound hooks
+pub fn sandbox_settings(settings: &TenantSettings) -> TenantSettings {
+    let mut sandboxed = settings.clone();
+    sandboxed.custom_domain = None;
+    sandboxed.notifications.webhook_url = None;
+    sandboxed.notifications.slack_webhook = None;
+    sandboxed
+}
+
+/// Pseudonymize personal data in collected tenant data in place
+pub fn anonymize_archive_data(data: &mut TenantArchiveData, salt: &str) {
+    data.tenant.admin_email = anonymize_email(&data.tenant.admin_email, salt);
+    data.tenant.settings = sandbox_settings(&data.tenant.settings);
+
+    for user in &mut data.users {
+        user.email = user.email.as_deref().map(|email| anonymize_email(email, salt));
+    }
+
+    for file in &mut data.files {
+        let anonymized = anonymize_filename(&file.filename, salt);
+        file.storage_path = match file.storage_path.rsplit_once('/') {
+            Some((directory, _)) => format!("{}/{}", directory, anonymized),
+            None => anonymized.clone(),
+        };
+        file.filename = anonymized;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_email_pseudonyms_are_stable_per_salt() {
+        let first = anonymize_email("Jane@Acme.com", "clone-1");
+        assert_eq!(first, anonymize_email("jane@acme.com", "clone-1"));
+        assert_ne!(first, anonymize_email("jane@acme.com", "clone-2"));
+        assert!(first.ends_with("@example.invalid"));
+        assert!(!first.contains("jane"));
+    }
+
+    #[test]
+    fn test_filename_keeps_extension() {
+        let anonymized = anonymize_filename("Q3 Payroll.XLSX", "clone-1");
+        assert!(anonymized.starts_with("file-"));
+        assert!(anonymized.ends_with(".xlsx"));
+        assert!(!anonymize_filename("README", "clone-1").contains('.'));
+    }
+
+    #[test]
+    fn test_sandbox_settings_strip_outbound_integrations() {
+        let mut settings = TenantSettings::default();
+        settings.custom_domain = Some("app.acme.com".to_string());
+        settings.notifications.webhook_url = Some("https://hooks.acme.com".to_string());
+        settings.notifications.slack_webhook = Some("https://hooks.slack.com/x".to_string());
+
+        let sandboxed = sandbox_settings(&settings);
+        assert!(sandboxed.custom_domain.is_none());
+        assert!(sandboxed.notifications.webhook_url.is_none());
+        assert!(sandboxed.notifications.slack_webhook.is_none());
+        assert_eq!(sandboxed.branding.theme, settings.branding.theme);
+    }
+}
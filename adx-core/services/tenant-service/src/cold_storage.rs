@@ -0,0 +1,144 @@
+use chrono::{DateTime, Duration, Utc};
+use flate2::read::GzDecoder;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use std::io::{Read, Write};
+
+use crate::export::TenantArchive;
+use crate::models::*;
+use adx_shared::types::{SubscriptionTier, UserId};
+
+// Cold storage for archived tenants. An archived tenant's data is written as a
+// gzip-compressed tenant archive and its hot database resources are released;
+// the tenant row stays so admins can still see and restore it.
+
+/// Where cold archives are written when an archive request does not name a destination
+pub const DEFAULT_COLD_STORAGE_DIR: &str = "/tmp/adx-core/tenant-cold-storage";
+/// Storage class recorded for archives written by this build
+pub const COLD_STORAGE_CLASS: &str = "cold";
+
+/// How long a restore may take before it counts as an SLA breach
+pub fn restore_sla(tier: &SubscriptionTier) -> Duration {
+    match tier {
+        SubscriptionTier::Free => Duration::hours(72),
+        SubscriptionTier::Professional => Duration::hours(24),
+        SubscriptionTier::Enterprise | SubscriptionTier::Custom => Duration::hours(4),
+    }
+}
+
+/// Whether a tenant in this state can be moved to cold storage
+pub fn check_archivable(tenant: &Tenant, active_children: usize) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+
+    match tenant.status {
+        TenantStatus::Archived => errors.push("Tenant is already archived".to_string()),
+        TenantStatus::ReadOnly => errors.push("Tenant is frozen for another operation".to_string()),
+        TenantStatus::Pending => errors.push("Tenant has not finished provisioning".to_string()),
+        TenantStatus::PendingDeletion => errors.push("Tenant is scheduled for deletion".to_string()),
+        TenantStatus::Active | TenantStatus::Suspended | TenantStatus::Cancelled => {}
+    }
+
+    if active_children > 0 {
+        errors.push(format!("Tenant has {} sub-tenant(s) that are not archived", active_children));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+pub fn compress_archive(archive: &TenantArchive) -> anyhow::Result<Vec<u8>> {
+    let json = serde_json::to_vec(archive)?;
+    let mut encoder = GzEncoder::new(Vec::new(), Compression::best());
+    encoder.write_all(&json)?;
+    Ok(encoder.finish()?)
+}
+
+pub fn decompress_archive(bytes: &[u8]) -> anyhow::Result<TenantArchive> {
+    let mut json = Vec::new();
+    GzDecoder::new(bytes).read_to_end(&mut json)?;
+    Ok(serde_json::from_slice(&json)?)
+}
+
+impl TenantRestoreRecord {
+    pub fn start(requested_by: Option<UserId>, tier: &SubscriptionTier, now: DateTime<Utc>) -> Self {
+        Self {
+            requested_at: now,
+            requested_by,
+            sla_deadline: now + restore_sla(tier),
+            completed_at: None,
+            sla_breached: false,
+        }
+    }
+
+    pub fn complete(&mut self, now: DateTime<Utc>) {
+        self.completed_at = Some(now);
+        self.sla_breached = now > self.sla_deadline;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::export::{build_archive, verify_archive, TenantArchiveData};
+    use adx_shared::types::{TenantIsolationLevel, TenantQuotas};
+
+    fn tenant(status: TenantStatus) -> Tenant {
+        Tenant {
+            id: "tenant-1".to_string(),
+            name: "Acme".to_string(),
+            slug: "acme".to_string(),
+            admin_email: "admin@acme.test".to_string(),
+            subscription_tier: SubscriptionTier::Professional,
+            isolation_level: TenantIsolationLevel::Schema,
+            quotas: TenantQuotas::default(),
+            features: vec![],
+            settings: TenantSettings::default(),
+            status,
+            parent_tenant_id: None,
+            inherit_settings: false,
+            region: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_compressed_archive_round_trips() {
+        let data = TenantArchiveData {
+            tenant: tenant(TenantStatus::Active),
+            users: vec![],
+            memberships: vec![],
+            files: vec![],
+            workflow_history: vec![],
+        };
+        let archive = build_archive(data, "production", None).unwrap();
+
+        let restored = decompress_archive(&compress_archive(&archive).unwrap()).unwrap();
+        assert_eq!(restored.manifest.archive_id, archive.manifest.archive_id);
+        assert!(verify_archive(&restored).is_ok());
+    }
+
+    #[test]
+    fn test_restore_sla_tracking() {
+        let requested_at = Utc::now();
+        let mut restore = TenantRestoreRecord::start(None, &SubscriptionTier::Enterprise, requested_at);
+        assert_eq!(restore.sla_deadline - requested_at, Duration::hours(4));
+
+        restore.complete(requested_at + Duration::hours(5));
+        assert!(restore.sla_breached);
+
+        let mut restore = TenantRestoreRecord::start(None, &SubscriptionTier::Free, requested_at);
+        restore.complete(requested_at + Duration::hours(5));
+        assert!(!restore.sla_breached);
+    }
+
+    #[test]
+    fn test_archivable_states() {
+        assert!(check_archivable(&tenant(TenantStatus::Suspended), 0).is_ok());
+        assert_eq!(check_archivable(&tenant(TenantStatus::Archived), 2).unwrap_err().len(), 2);
+        assert!(check_archivable(&tenant(TenantStatus::ReadOnly), 0).is_err());
+    }
+}
@@ -0,0 +1,291 @@
+use async_trait::async_trait;
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+use crate::models::*;
+use adx_shared::custom_domains::INTERNAL_TOKEN_HEADER;
+use adx_shared::types::{TenantId, UserId};
+
+// Staged tenant deletion. A terminated tenant is first soft-deleted: it stops
+// serving requests but nothing is removed, and the deletion can still be
+// cancelled. Once the retention period ends every service purges the tenant's
+// data, and security-service signs a destruction certificate listing what was
+// purged. The deletion record outlives the tenant row as compliance evidence.
+
+pub const DEFAULT_DELETION_RETENTION_DAYS: u32 = 30;
+/// How often a waiting deletion wakes up to check whether it was cancelled
+pub const DELETION_CHECK_INTERVAL_SECS: u64 = 3600;
+/// A service purging a large tenant can take a while to answer
+const PURGE_REQUEST_TIMEOUT_SECS: u64 = 300;
+/// Services holding tenant data, purged in this order. tenant-service goes last
+/// because purging it removes the tenant row. Audit logs in security-service
+/// are kept for their own retention period and are not purged here.
+pub const PURGE_TARGET_SERVICES: [&str; 9] = [
+    "ai-service",
+    "workflow-service",
+    "module-service",
+    "file-service",
+    "white-label-service",
+    "license-service",
+    "user-service",
+    "auth-service",
+    "tenant-service",
+];
+
+/// Whether a tenant in this state can be scheduled for deletion
+pub fn check_deletable(tenant: &Tenant, active_children: usize) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+
+    match tenant.status {
+        TenantStatus::PendingDeletion => errors.push("Tenant is already scheduled for deletion".to_string()),
+        TenantStatus::ReadOnly => errors.push("Tenant is frozen for another operation".to_string()),
+        _ => {}
+    }
+
+    if active_children > 0 {
+        errors.push(format!("Tenant has {} sub-tenant(s) that are not being deleted", active_children));
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+impl TenantDeletionRecord {
+    pub fn start(
+        tenant: &Tenant,
+        requested_by: UserId,
+        reason: Option<String>,
+        retention_days: u32,
+        now: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            tenant_id: tenant.id.clone(),
+            tenant_name: tenant.name.clone(),
+            stage: TenantDeletionStage::SoftDeleted,
+            previous_status: tenant.status.clone(),
+            requested_by,
+            reason,
+            soft_deleted_at: now,
+            purge_after: now + Duration::days(retention_days as i64),
+            purged_services: Vec::new(),
+            purged_at: None,
+            certificate_id: None,
+            cancelled_at: None,
+        }
+    }
+
+    pub fn purge_due(&self, now: DateTime<Utc>) -> bool {
+        self.stage == TenantDeletionStage::SoftDeleted && now >= self.purge_after
+    }
+
+    /// A deletion can be called off until the purge starts
+    pub fn can_cancel(&self) -> bool {
+        self.stage == TenantDeletionStage::SoftDeleted
+    }
+
+    /// Services still to purge, in purge order, so a retried workflow skips
+    /// the ones that already finished
+    pub fn pending_purges(&self) -> Vec<&'static str> {
+        PURGE_TARGET_SERVICES
+            .iter()
+            .copied()
+            .filter(|service| !self.purged_services.iter().any(|p| p.service == *service))
+            .collect()
+    }
+}
+
+/// Where each service holding tenant data is reached when no
+/// `<SERVICE>_URL` variable overrides it
+const DEFAULT_SERVICE_URLS: [(&str, &str); 9] = [
+    ("auth-service", "http://localhost:8081"),
+    ("user-service", "http://localhost:8082"),
+    ("file-service", "http://localhost:8083"),
+    ("workflow-service", "http://localhost:8084"),
+    ("module-service", "http://localhost:8086"),
+    ("license-service", "http://localhost:8087"),
+    ("security-service", "http://localhost:8088"),
+    ("white-label-service", "http://localhost:8089"),
+    ("ai-service", "http://localhost:8086"),
+];
+
+/// Endpoint a service serves to delete everything it holds for a tenant
+pub fn tenant_purge_path(tenant_id: &TenantId) -> String {
+    format!("/api/v1/internal/tenants/{}/purge", tenant_id)
+}
+
+/// What a service's purge endpoint answers once the tenant's data is gone
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantPurgeResponse {
+    pub records_deleted: u64,
+}
+
+/// Body of security-service's certificate issuance endpoint
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct IssueDestructionCertificateRequest {
+    pub certificate_id: String,
+    pub tenant_id: TenantId,
+    pub tenant_name: String,
+    pub requested_by: UserId,
+    pub soft_deleted_at: DateTime<Utc>,
+    pub purged_at: DateTime<Utc>,
+    pub purged_services: Vec<ServicePurgeResult>,
+}
+
+/// The other services a purge reaches: each one deletes its share of the
+/// tenant's data, then security-service certifies the whole purge
+#[async_trait]
+pub trait TenantPurgeClient: Send + Sync {
+    /// Purge the tenant from a service and return how many records went
+    async fn purge_service(&self, service: &str, tenant_id: &TenantId) -> anyhow::Result<u64>;
+
+    async fn issue_destruction_certificate(&self, request: &IssueDestructionCertificateRequest) -> anyhow::Result<()>;
+}
+
+pub struct HttpTenantPurgeClient {
+    client: reqwest::Client,
+    service_urls: HashMap<String, String>,
+    internal_token: Option<String>,
+}
+
+impl HttpTenantPurgeClient {
+    pub fn new(service_urls: HashMap<String, String>, internal_token: Option<String>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(std::time::Duration::from_secs(PURGE_REQUEST_TIMEOUT_SECS))
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+
+        Self { client, service_urls, internal_token }
+    }
+
+    /// Service URLs from `AUTH_SERVICE_URL`, `WHITE_LABEL_SERVICE_URL` and so
+    /// on, falling back to the local defaults; the token sent to internal
+    /// endpoints from `INTERNAL_SERVICE_TOKEN`
+    pub fn from_env() -> Self {
+        let service_urls = DEFAULT_SERVICE_URLS
+            .iter()
+            .map(|(service, default_url)| {
+                let variable = format!("{}_URL", service.to_ascii_uppercase().replace('-', "_"));
+                let url = std::env::var(variable).unwrap_or_else(|_| default_url.to_string());
+                (service.to_string(), url)
+            })
+            .collect();
+        let internal_token = std::env::var("INTERNAL_SERVICE_TOKEN").ok().filter(|token| !token.is_empty());
+
+        Self::new(service_urls, internal_token)
+    }
+
+    fn url(&self, service: &str, path: &str) -> anyhow::Result<String> {
+        let base_url = self.service_urls
+            .get(service)
+            .ok_or_else(|| anyhow::anyhow!("No URL configured for {}", service))?;
+        Ok(format!("{}{}", base_url.trim_end_matches('/'), path))
+    }
+
+    fn post(&self, url: String) -> reqwest::RequestBuilder {
+        let request = self.client.post(url);
+        match &self.internal_token {
+            Some(token) => request.header(INTERNAL_TOKEN_HEADER, token),
+            None => request,
+        }
+    }
+}
+
+#[async_trait]
+impl TenantPurgeClient for HttpTenantPurgeClient {
+    async fn purge_service(&self, service: &str, tenant_id: &TenantId) -> anyhow::Result<u64> {
+        let url = self.url(service, &tenant_purge_path(tenant_id))?;
+        let response = self.post(url).send().await?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "{} refused to purge tenant {}: {}",
+                service,
+                tenant_id,
+                response.status()
+            ));
+        }
+
+        let purged: TenantPurgeResponse = response.json().await?;
+        Ok(purged.records_deleted)
+    }
+
+    async fn issue_destruction_certificate(&self, request: &IssueDestructionCertificateRequest) -> anyhow::Result<()> {
+        let url = self.url("security-service", "/api/v1/destruction-certificates")?;
+        let response = self.post(url).json(request).send().await?;
+        if !response.status().is_success() {
+            return Err(anyhow::anyhow!(
+                "security-service refused destruction certificate {}: {}",
+                request.certificate_id,
+                response.status()
+            ));
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adx_shared::types::{SubscriptionTier, TenantIsolationLevel, TenantQuotas};
+
+    fn tenant(status: TenantStatus) -> Tenant {
+        Tenant {
+            id: "tenant-1".to_string(),
+            name: "Acme".to_string(),
+            slug: "acme".to_string(),
+            admin_email: "admin@acme.test".to_string(),
+            subscription_tier: SubscriptionTier::Professional,
+            isolation_level: TenantIsolationLevel::Schema,
+            quotas: TenantQuotas::default(),
+            features: vec![],
+            settings: TenantSettings::default(),
+            status,
+            parent_tenant_id: None,
+            inherit_settings: false,
+            region: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_deletable_states() {
+        assert!(check_deletable(&tenant(TenantStatus::Suspended), 0).is_ok());
+        assert!(check_deletable(&tenant(TenantStatus::Archived), 0).is_ok());
+        assert_eq!(check_deletable(&tenant(TenantStatus::PendingDeletion), 1).unwrap_err().len(), 2);
+        assert!(check_deletable(&tenant(TenantStatus::ReadOnly), 0).is_err());
+    }
+
+    #[test]
+    fn test_retention_window_and_cancellation() {
+        let now = Utc::now();
+        let mut record = TenantDeletionRecord::start(&tenant(TenantStatus::Active), "user-1".to_string(), None, 30, now);
+
+        assert!(!record.purge_due(now + Duration::days(29)));
+        assert!(record.purge_due(now + Duration::days(30)));
+        assert!(record.can_cancel());
+
+        record.stage = TenantDeletionStage::Purging;
+        assert!(!record.can_cancel());
+        assert!(!record.purge_due(now + Duration::days(31)));
+    }
+
+    #[test]
+    fn test_pending_purges_skip_finished_services() {
+        let mut record = TenantDeletionRecord::start(&tenant(TenantStatus::Active), "user-1".to_string(), None, 30, Utc::now());
+        assert_eq!(record.pending_purges().len(), PURGE_TARGET_SERVICES.len());
+
+        record.purged_services.push(ServicePurgeResult {
+            service: "file-service".to_string(),
+            records_deleted: 12,
+            purged_at: Utc::now(),
+        });
+
+        let pending = record.pending_purges();
+        assert!(!pending.contains(&"file-service"));
+        assert_eq!(pending.last(), Some(&"tenant-service"));
+    }
+}
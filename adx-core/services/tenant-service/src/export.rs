@@ -0,0 +1,279 @@
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use uuid::Uuid;
+
+use crate::models::*;
+use adx_shared::types::{TenantId, UserId};
+
+// Versioned archive format used to move a tenant between environments.
+// The format version covers the archive layout itself; the schema version
+// records which database schema the exported rows were read from.
+
+/// Archive layout written by this build
+pub const TENANT_ARCHIVE_FORMAT_VERSION: u32 = 1;
+/// Oldest archive layout this build can still read
+pub const MIN_SUPPORTED_FORMAT_VERSION: u32 = 1;
+/// Schema version of tenant-owned tables; bump with shared migrations that change them
+pub const TENANT_SCHEMA_VERSION: u32 = 17;
+/// Tenant service tables were introduced in migration 006
+pub const MIN_IMPORTABLE_SCHEMA_VERSION: u32 = 6;
+/// Where archives are written when an export does not name a destination
+pub const DEFAULT_EXPORT_DIR: &str = "/tmp/adx-core/tenant-exports";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedUser {
+    pub user_id: UserId,
+    pub email: Option<String>,
+    pub role: TenantRole,
+    pub permissions: Vec<String>,
+    pub joined_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ArchivedFileMetadata {
+    pub file_id: String,
+    pub filename: String,
+    pub mime_type: String,
+    pub size_bytes: u64,
+    pub storage_path: String,
+    pub checksum: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Workflow runs are referenced rather than copied; Temporal history stays
+/// in the source environment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowHistoryReference {
+    pub workflow_id: String,
+    pub run_id: Option<String>,
+    pub workflow_type: String,
+    pub status: String,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantArchiveData {
+    pub tenant: Tenant,
+    pub users: Vec<ArchivedUser>,
+    pub memberships: Vec<TenantMembership>,
+    pub files: Vec<ArchivedFileMetadata>,
+    pub workflow_history: Vec<WorkflowHistoryReference>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TenantArchiveSections {
+    pub users: usize,
+    pub memberships: usize,
+    pub files: usize,
+    pub workflow_history: usize,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantArchiveManifest {
+    pub archive_id: String,
+    pub format_version: u32,
+    pub schema_version: u32,
+    pub source_tenant_id: TenantId,
+    pub source_environment: String,
+    pub exported_at: DateTime<Utc>,
+    pub exported_by: Option<UserId>,
+    pub sections: TenantArchiveSections,
+    /// SHA-256 of the serialized archive data
+    pub checksum: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantArchive {
+    pub manifest: TenantArchiveManifest,
+    pub data: TenantArchiveData,
+}
+
+#[derive(Debug, thiserror::Error, PartialEq, Eq)]
+pub enum ArchiveCompatibilityError {
+    #[error("Unsupported archive format version {found} (supported: {min}-{max})")]
+    UnsupportedFormatVersion { found: u32, min: u32, max: u32 },
+    #[error("Archive schema version {found} is older than the minimum importable version {min}")]
+    SchemaTooOld { found: u32, min: u32 },
+    #[error("Archive schema version {found} is newer than this environment's schema version {current}")]
+    SchemaTooNew { found: u32, current: u32 },
+    #[error("Archive checksum mismatch (expected {expected}, got {actual})")]
+    ChecksumMismatch { expected: String, actual: String },
+    #[error("Archive section counts do not match its contents")]
+    SectionCountMismatch,
+}
+
+pub fn compute_archive_checksum(data: &TenantArchiveData) -> Result<String, serde_json::Error> {
+    let bytes = serde_json::to_vec(data)?;
+    Ok(format!("{:x}", Sha256::digest(&bytes)))
+}
+
+fn sections_for(data: &TenantArchiveData) -> TenantArchiveSections {
+    TenantArchiveSections {
+        users: data.users.len(),
+        memberships: data.memberships.len(),
+        files: data.files.len(),
+        workflow_history: data.workflow_history.len(),
+    }
+}
+
+/// Wrap collected tenant data in a manifest stamped with the current versions
+pub fn build_archive(
+    data: TenantArchiveData,
+    source_environment: &str,
+    exported_by: Option<UserId>,
+) -> Result<TenantArchive, serde_json::Error> {
+    let manifest = TenantArchiveManifest {
+        archive_id: Uuid::new_v4().to_string(),
+        format_version: TENANT_ARCHIVE_FORMAT_VERSION,
+        schema_version: TENANT_SCHEMA_VERSION,
+        source_tenant_id: data.tenant.id.clone(),
+        source_environment: source_environment.to_string(),
+        exported_at: Utc::now(),
+        exported_by,
+        sections: sections_for(&data),
+        checksum: compute_archive_checksum(&data)?,
+    };
+
+    Ok(TenantArchive { manifest, data })
+}
+
+/// Check that an archive was written in a format and schema this build can restore
+pub fn check_archive_compatibility(manifest: &TenantArchiveManifest) -> Result<(), ArchiveCompatibilityError> {
+    if manifest.format_version < MIN_SUPPORTED_FORMAT_VERSION
+        || manifest.format_version > TENANT_ARCHIVE_FORMAT_VERSION
+    {
+        return Err(ArchiveCompatibilityError::UnsupportedFormatVersion {
+            found: manifest.format_version,
+            min: MIN_SUPPORTED_FORMAT_VERSION,
+            max: TENANT_ARCHIVE_FORMAT_VERSION,
+        });
+    }
+
+    if manifest.schema_version < MIN_IMPORTABLE_SCHEMA_VERSION {
+        return Err(ArchiveCompatibilityError::SchemaTooOld {
+            found: manifest.schema_version,
+            min: MIN_IMPORTABLE_SCHEMA_VERSION,
+        });
+    }
+
+    // Restoring rows from a newer schema could silently drop columns
+    if manifest.schema_version > TENANT_SCHEMA_VERSION {
+        return Err(ArchiveCompatibilityError::SchemaTooNew {
+            found: manifest.schema_version,
+            current: TENANT_SCHEMA_VERSION,
+        });
+    }
+
+    Ok(())
+}
+
+/// Full integrity check: version compatibility, section counts and checksum
+pub fn verify_archive(archive: &TenantArchive) -> Result<(), ArchiveCompatibilityError> {
+    check_archive_compatibility(&archive.manifest)?;
+
+    if archive.manifest.sections != sections_for(&archive.data) {
+        return Err(ArchiveCompatibilityError::SectionCountMismatch);
+    }
+
+    let actual = compute_archive_checksum(&archive.data).map_err(|_| {
+        ArchiveCompatibilityError::ChecksumMismatch {
+            expected: archive.manifest.checksum.clone(),
+            actual: String::new(),
+        }
+    })?;
+
+    if actual != archive.manifest.checksum {
+        return Err(ArchiveCompatibilityError::ChecksumMismatch {
+            expected: archive.manifest.checksum.clone(),
+            actual,
+        });
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adx_shared::types::{SubscriptionTier, TenantIsolationLevel, TenantQuotas};
+
+    fn sample_data() -> TenantArchiveData {
+        TenantArchiveData {
+            tenant: Tenant {
+                id: "tenant-1".to_string(),
+                name: "Acme".to_string(),
+                slug: "acme".to_string(),
+                admin_email: "admin@acme.test".to_string(),
+                subscription_tier: SubscriptionTier::Professional,
+                isolation_level: TenantIsolationLevel::Schema,
+                quotas: TenantQuotas::default(),
+                features: vec!["api_access".to_string()],
+                settings: TenantSettings::default(),
+                status: TenantStatus::Active,
+                parent_tenant_id: None,
+                inherit_settings: false,
+                region: None,
+                created_at: Utc::now(),
+                updated_at: Utc::now(),
+            },
+            users: vec![ArchivedUser {
+                user_id: "user-1".to_string(),
+                email: None,
+                role: TenantRole::Owner,
+                permissions: vec![],
+                joined_at: None,
+            }],
+            memberships: vec![],
+            files: vec![],
+            workflow_history: vec![],
+        }
+    }
+
+    #[test]
+    fn test_built_archive_verifies() {
+        let archive = build_archive(sample_data(), "staging", None).unwrap();
+
+        assert_eq!(archive.manifest.format_version, TENANT_ARCHIVE_FORMAT_VERSION);
+        assert_eq!(archive.manifest.schema_version, TENANT_SCHEMA_VERSION);
+        assert_eq!(archive.manifest.source_tenant_id, "tenant-1");
+        assert_eq!(archive.manifest.sections.users, 1);
+        assert!(verify_archive(&archive).is_ok());
+    }
+
+    #[test]
+    fn test_tampered_archive_fails_checksum() {
+        let mut archive = build_archive(sample_data(), "staging", None).unwrap();
+        archive.data.tenant.name = "Tampered".to_string();
+
+        assert!(matches!(
+            verify_archive(&archive),
+            Err(ArchiveCompatibilityError::ChecksumMismatch { .. })
+        ));
+    }
+
+    #[test]
+    fn test_version_compatibility() {
+        let mut archive = build_archive(sample_data(), "staging", None).unwrap();
+
+        archive.manifest.format_version = TENANT_ARCHIVE_FORMAT_VERSION + 1;
+        assert!(matches!(
+            check_archive_compatibility(&archive.manifest),
+            Err(ArchiveCompatibilityError::UnsupportedFormatVersion { .. })
+        ));
+
+        archive.manifest.format_version = TENANT_ARCHIVE_FORMAT_VERSION;
+        archive.manifest.schema_version = TENANT_SCHEMA_VERSION + 1;
+        assert!(matches!(
+            check_archive_compatibility(&archive.manifest),
+            Err(ArchiveCompatibilityError::SchemaTooNew { .. })
+        ));
+
+        archive.manifest.schema_version = MIN_IMPORTABLE_SCHEMA_VERSION - 1;
+        assert!(matches!(
+            check_archive_compatibility(&archive.manifest),
+            Err(ArchiveCompatibilityError::SchemaTooOld { .. })
+        ));
+    }
+}
@@ -0,0 +1,213 @@
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+
+use crate::models::*;
+use adx_shared::feature_flags::FeatureFlagSet;
+use adx_shared::types::SubscriptionTier;
+
+// Feature flag evaluation. For a given tenant a flag resolves, in order, from:
+// the flag's kill switch, a per-tenant override, the tenant's plan features,
+// the flag's tier list, its percentage rollout, and finally its default.
+
+/// Plan feature that enables every flag (used by the custom template)
+pub const ALL_FEATURES: &str = "all_features";
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum FlagEvaluationReason {
+    Inactive,
+    Override,
+    Plan,
+    Tier,
+    Rollout,
+    Default,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FlagEvaluation {
+    pub key: String,
+    pub enabled: bool,
+    pub reason: FlagEvaluationReason,
+}
+
+pub fn validate_flag_key(key: &str) -> Result<(), String> {
+    if key.len() < 2 || key.len() > 64 {
+        return Err("Flag key must be between 2 and 64 characters".to_string());
+    }
+
+    if !key.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '.' || c == '-') {
+        return Err("Flag key may only contain lowercase letters, digits, '_', '.' and '-'".to_string());
+    }
+
+    Ok(())
+}
+
+pub fn validate_rollout_percentage(percentage: u8) -> Result<(), String> {
+    if percentage > 100 {
+        return Err("Rollout percentage must be between 0 and 100".to_string());
+    }
+    Ok(())
+}
+
+/// Stable 0-99 bucket for a tenant within a flag's rollout. Hashing the flag
+/// key in means each flag rolls out to a different slice of tenants.
+pub fn rollout_bucket(flag_key: &str, tenant_id: &str) -> u8 {
+    let digest = Sha256::digest(format!("{}:{}", flag_key, tenant_id).as_bytes());
+    let value = u32::from_be_bytes([digest[0], digest[1], digest[2], digest[3]]);
+    (value % 100) as u8
+}
+
+pub fn evaluate_flag(
+    flag: &FeatureFlag,
+    tenant_id: &str,
+    tier: &SubscriptionTier,
+    plan_features: &[String],
+    tenant_override: Option<&TenantFlagOverride>,
+) -> FlagEvaluation {
+    let (enabled, reason) = if !flag.is_active {
+        (false, FlagEvaluationReason::Inactive)
+    } else if let Some(tenant_override) = tenant_override {
+        (tenant_override.enabled, FlagEvaluationReason::Override)
+    } else if plan_features.iter().any(|feature| feature == &flag.key || feature == ALL_FEATURES) {
+        (true, FlagEvaluationReason::Plan)
+    } else if flag.enabled_tiers.contains(tier) {
+        (true, FlagEvaluationReason::Tier)
+    } else if flag.rollout_percentage > 0 && rollout_bucket(&flag.key, tenant_id) < flag.rollout_percentage {
+        (true, FlagEvaluationReason::Rollout)
+    } else {
+        (flag.default_enabled, FlagEvaluationReason::Default)
+    };
+
+    FlagEvaluation {
+        key: flag.key.clone(),
+        enabled,
+        reason,
+    }
+}
+
+/// Evaluate every defined flag for a tenant. Plan features with no flag
+/// definition are reported as enabled so existing feature strings keep working.
+pub fn evaluate_tenant_flags(
+    flags: &[FeatureFlag],
+    tenant: &Tenant,
+    overrides: &[TenantFlagOverride],
+) -> Vec<FlagEvaluation> {
+    let overrides: HashMap<&str, &TenantFlagOverride> = overrides
+        .iter()
+        .map(|tenant_override| (tenant_override.flag_key.as_str(), tenant_override))
+        .collect();
+
+    let mut evaluations: Vec<FlagEvaluation> = flags
+        .iter()
+        .map(|flag| {
+            evaluate_flag(
+                flag,
+                &tenant.id,
+                &tenant.subscription_tier,
+                &tenant.features,
+                overrides.get(flag.key.as_str()).copied(),
+            )
+        })
+        .collect();
+
+    for feature in &tenant.features {
+        if feature != ALL_FEATURES && !flags.iter().any(|flag| &flag.key == feature) {
+            evaluations.push(FlagEvaluation {
+                key: feature.clone(),
+                enabled: true,
+                reason: FlagEvaluationReason::Plan,
+            });
+        }
+    }
+
+    evaluations
+}
+
+pub fn to_flag_set(tenant_id: &str, evaluations: &[FlagEvaluation]) -> FeatureFlagSet {
+    FeatureFlagSet::new(
+        tenant_id,
+        evaluations
+            .iter()
+            .map(|evaluation| (evaluation.key.clone(), evaluation.enabled))
+            .collect(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::Utc;
+
+    fn flag(key: &str) -> FeatureFlag {
+        FeatureFlag {
+            key: key.to_string(),
+            description: None,
+            default_enabled: false,
+            enabled_tiers: vec![],
+            rollout_percentage: 0,
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    fn override_for(key: &str, enabled: bool) -> TenantFlagOverride {
+        TenantFlagOverride {
+            tenant_id: "tenant-1".to_string(),
+            flag_key: key.to_string(),
+            enabled,
+            reason: None,
+            updated_by: None,
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_evaluation_precedence() {
+        let tier = SubscriptionTier::Professional;
+        let plan = vec!["sso".to_string()];
+
+        let evaluation = evaluate_flag(&flag("sso"), "tenant-1", &tier, &plan, None);
+        assert_eq!((evaluation.enabled, evaluation.reason), (true, FlagEvaluationReason::Plan));
+
+        let disabled = override_for("sso", false);
+        let evaluation = evaluate_flag(&flag("sso"), "tenant-1", &tier, &plan, Some(&disabled));
+        assert_eq!((evaluation.enabled, evaluation.reason), (false, FlagEvaluationReason::Override));
+
+        let mut killed = flag("sso");
+        killed.is_active = false;
+        let enabled = override_for("sso", true);
+        let evaluation = evaluate_flag(&killed, "tenant-1", &tier, &plan, Some(&enabled));
+        assert_eq!((evaluation.enabled, evaluation.reason), (false, FlagEvaluationReason::Inactive));
+
+        let mut tiered = flag("audit_logs");
+        tiered.enabled_tiers = vec![SubscriptionTier::Professional];
+        let evaluation = evaluate_flag(&tiered, "tenant-1", &tier, &[], None);
+        assert_eq!((evaluation.enabled, evaluation.reason), (true, FlagEvaluationReason::Tier));
+    }
+
+    #[test]
+    fn test_rollout_is_stable_and_proportional() {
+        assert_eq!(rollout_bucket("beta_editor", "tenant-1"), rollout_bucket("beta_editor", "tenant-1"));
+
+        let mut rollout = flag("beta_editor");
+        rollout.rollout_percentage = 30;
+        let enabled = (0..1000)
+            .filter(|i| {
+                evaluate_flag(&rollout, &format!("tenant-{}", i), &SubscriptionTier::Free, &[], None).enabled
+            })
+            .count();
+        assert!((200..400).contains(&enabled), "{} of 1000 tenants enabled", enabled);
+
+        rollout.rollout_percentage = 100;
+        assert!(evaluate_flag(&rollout, "tenant-1", &SubscriptionTier::Free, &[], None).enabled);
+    }
+
+    #[test]
+    fn test_flag_key_validation() {
+        assert!(validate_flag_key("ai.summaries-v2").is_ok());
+        assert!(validate_flag_key("Beta Editor").is_err());
+        assert!(validate_rollout_percentage(101).is_err());
+    }
+}
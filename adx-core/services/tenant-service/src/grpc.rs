@@ -0,0 +1,93 @@
+// Internal gRPC API
+//
+// Serves permission checks to the other services on GRPC_PORT, alongside the
+// REST API. Answers match GET /api/v1/tenants/:tenant_id/permissions/:user_id.
+
+use adx_proto::tenant::v1::{self as proto, permission_service_server::PermissionService};
+use adx_shared::telemetry::grpc_span;
+use tonic::{Request, Response, Status};
+use tracing::Instrument;
+
+use crate::handlers::TenantServiceState;
+
+pub use proto::permission_service_server::PermissionServiceServer;
+
+/// Fixed port for tenant service's internal gRPC API
+pub const GRPC_PORT: u16 = 50085;
+
+#[derive(Clone)]
+pub struct PermissionGrpcService {
+    tenant_service: TenantServiceState,
+}
+
+impl PermissionGrpcService {
+    pub fn new(tenant_service: TenantServiceState) -> Self {
+        Self { tenant_service }
+    }
+
+    pub fn into_server(self) -> PermissionServiceServer<Self> {
+        PermissionServiceServer::new(self)
+    }
+}
+
+#[tonic::async_trait]
+impl PermissionService for PermissionGrpcService {
+    async fn check_permission(
+        &self,
+        request: Request<proto::CheckPermissionRequest>,
+    ) -> Result<Response<proto::CheckPermissionResponse>, Status> {
+        let span = grpc_span("adx.tenant.v1.PermissionService/CheckPermission", &request);
+        let request = request.into_inner();
+
+        let allowed = self
+            .tenant_service
+            .validate_tenant_permission(&request.tenant_id, &request.user_id, &request.permission)
+            .instrument(span)
+            .await
+            .map_err(|e| Status::internal(e.to_string()))?;
+        Ok(Response::new(proto::CheckPermissionResponse { allowed }))
+    }
+
+    async fn list_permissions(
+        &self,
+        request: Request<proto::ListPermissionsRequest>,
+    ) -> Result<Response<proto::ListPermissionsResponse>, Status> {
+        let span = grpc_span("adx.tenant.v1.PermissionService/ListPermissions", &request);
+        let request = request.into_inner();
+
+        let context = self
+            .tenant_service
+            .get_tenant_context(&request.tenant_id, &request.user_id)
+            .instrument(span)
+            .await
+            .map_err(|e| context_status(&e.to_string()))?;
+        Ok(Response::new(proto::ListPermissionsResponse {
+            // Same spelling as the REST endpoint's serialized role
+            role: format!("{:?}", context.user_role),
+            permissions: context.user_permissions,
+        }))
+    }
+}
+
+/// Unknown tenants and non-members are NOT_FOUND, which the REST endpoint
+/// reports as 403
+fn context_status(message: &str) -> Status {
+    if message.contains("not found") || message.contains("does not have access") {
+        Status::not_found(message)
+    } else {
+        Status::internal(message)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tonic::Code;
+
+    #[test]
+    fn test_non_members_are_not_found() {
+        assert_eq!(context_status("Tenant not found").code(), Code::NotFound);
+        assert_eq!(context_status("User does not have access to tenant").code(), Code::NotFound);
+        assert_eq!(context_status("connection refused").code(), Code::Internal);
+    }
+}
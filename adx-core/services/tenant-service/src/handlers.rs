@@ -1,13 +1,20 @@
 use std::sync::Arc;
+use std::convert::Infallible;
 use axum::{
     extract::{Path, Query, State},
     http::StatusCode,
+    response::sse::{Event, KeepAlive, Sse},
     response::Json,
 };
+use futures::stream::{self, Stream};
 use serde::Deserialize;
+use tokio::sync::broadcast::error::RecvError;
 
+use crate::feature_flags::FlagEvaluation;
+use crate::lifecycle_events::{TenantLifecycleEvent, DEFAULT_EVENT_PAGE_SIZE};
 use crate::models::*;
 use crate::services::TenantService;
+use adx_shared::feature_flags::FeatureFlagSet;
 use adx_shared::types::{TenantId, UserId, PaginatedResponse, PaginationInfo};
 
 pub type TenantServiceState = Arc<TenantService>;
@@ -182,6 +189,1152 @@ pub async fn delete_tenant(
     }
 }
 
+// Sub-tenant hierarchy handlers
+pub async fn create_sub_tenant(
+    State(service): State<TenantServiceState>,
+    Path(id): Path<TenantId>,
+    Json(request): Json<CreateSubTenantRequest>,
+) -> Result<(StatusCode, Json<Tenant>), (StatusCode, Json<serde_json::Value>)> {
+    match service.create_sub_tenant(&id, request).await {
+        Ok(tenant) => Ok((StatusCode::CREATED, Json(tenant))),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "SUB_TENANT_CREATION_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn list_sub_tenants(
+    State(service): State<TenantServiceState>,
+    Path(id): Path<TenantId>,
+) -> Result<Json<Vec<Tenant>>, (StatusCode, Json<serde_json::Value>)> {
+    match service.list_sub_tenants(&id).await {
+        Ok(tenants) => Ok(Json(tenants)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "INTERNAL_ERROR",
+                    "message": e.to_string()
+                }
+            })),
+        )),
+    }
+}
+
+pub async fn get_tenant_hierarchy(
+    State(service): State<TenantServiceState>,
+    Path(id): Path<TenantId>,
+) -> Result<Json<crate::hierarchy::TenantHierarchyNode>, (StatusCode, Json<serde_json::Value>)> {
+    match service.get_tenant_hierarchy(&id).await {
+        Ok(hierarchy) => Ok(Json(hierarchy)),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "TENANT_HIERARCHY_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn get_tenant_placement(
+    State(service): State<TenantServiceState>,
+    Path(id): Path<TenantId>,
+) -> Result<Json<adx_shared::database::isolation::TenantPlacement>, (StatusCode, Json<serde_json::Value>)> {
+    match service.get_tenant_placement(&id).await {
+        Ok(placement) => Ok(Json(placement)),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "TENANT_PLACEMENT_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn get_tenant_archive(
+    State(service): State<TenantServiceState>,
+    Path(id): Path<TenantId>,
+) -> Result<Json<TenantArchiveRecord>, (StatusCode, Json<serde_json::Value>)> {
+    match service.get_tenant_archive(&id).await {
+        Ok(Some(record)) => Ok(Json(record)),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "TENANT_ARCHIVE_NOT_FOUND",
+                    "message": "Tenant archive not found"
+                }
+            })),
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "INTERNAL_ERROR",
+                    "message": e.to_string()
+                }
+            })),
+        )),
+    }
+}
+
+pub async fn list_tenant_archives(
+    State(service): State<TenantServiceState>,
+) -> Result<Json<Vec<TenantArchiveRecord>>, (StatusCode, Json<serde_json::Value>)> {
+    match service.list_tenant_archives().await {
+        Ok(records) => Ok(Json(records)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "INTERNAL_ERROR",
+                    "message": e.to_string()
+                }
+            })),
+        )),
+    }
+}
+
+pub async fn get_tenant_deletion(
+    State(service): State<TenantServiceState>,
+    Path(id): Path<TenantId>,
+) -> Result<Json<TenantDeletionRecord>, (StatusCode, Json<serde_json::Value>)> {
+    match service.get_tenant_deletion(&id).await {
+        Ok(Some(record)) => Ok(Json(record)),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "TENANT_DELETION_NOT_FOUND",
+                    "message": "Tenant deletion not found"
+                }
+            })),
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "INTERNAL_ERROR",
+                    "message": e.to_string()
+                }
+            })),
+        )),
+    }
+}
+
+pub async fn list_tenant_deletions(
+    State(service): State<TenantServiceState>,
+) -> Result<Json<Vec<TenantDeletionRecord>>, (StatusCode, Json<serde_json::Value>)> {
+    match service.list_tenant_deletions().await {
+        Ok(records) => Ok(Json(records)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "INTERNAL_ERROR",
+                    "message": e.to_string()
+                }
+            })),
+        )),
+    }
+}
+
+pub async fn cancel_tenant_deletion(
+    State(service): State<TenantServiceState>,
+    Path(id): Path<TenantId>,
+) -> Result<Json<TenantDeletionRecord>, (StatusCode, Json<serde_json::Value>)> {
+    match service.cancel_tenant_deletion(&id).await {
+        Ok(record) => Ok(Json(record)),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::CONFLICT
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "TENANT_DELETION_CANCEL_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn detach_sub_tenant(
+    State(service): State<TenantServiceState>,
+    Path(id): Path<TenantId>,
+    Json(request): Json<DetachSubTenantRequest>,
+) -> Result<Json<Tenant>, (StatusCode, Json<serde_json::Value>)> {
+    match service.detach_sub_tenant(&id, request).await {
+        Ok(tenant) => Ok(Json(tenant)),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "SUB_TENANT_DETACH_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+// Trial lifecycle handlers
+pub async fn extend_tenant_trial(
+    State(service): State<TenantServiceState>,
+    Path(id): Path<TenantId>,
+    Json(request): Json<ExtendTrialRequest>,
+) -> Result<(StatusCode, Json<TrialSignalAccepted>), (StatusCode, Json<serde_json::Value>)> {
+    match service.extend_trial(&id, request).await {
+        Ok(accepted) => Ok((StatusCode::ACCEPTED, Json(accepted))),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "TRIAL_EXTEND_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn convert_tenant_trial(
+    State(service): State<TenantServiceState>,
+    Path(id): Path<TenantId>,
+    Json(request): Json<ConvertTrialRequest>,
+) -> Result<(StatusCode, Json<TrialSignalAccepted>), (StatusCode, Json<serde_json::Value>)> {
+    match service.convert_trial(&id, request).await {
+        Ok(accepted) => Ok((StatusCode::ACCEPTED, Json(accepted))),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "TRIAL_CONVERT_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+// Provisioning template handlers
+#[derive(Debug, Deserialize)]
+pub struct ListTemplatesQuery {
+    pub include_inactive: Option<bool>,
+}
+
+pub async fn create_tenant_template(
+    State(service): State<TenantServiceState>,
+    Json(request): Json<CreateTenantTemplateRequest>,
+) -> Result<(StatusCode, Json<TenantTemplate>), (StatusCode, Json<serde_json::Value>)> {
+    match service.create_template(request).await {
+        Ok(template) => Ok((StatusCode::CREATED, Json(template))),
+        Err(e) => {
+            let status = if e.to_string().contains("already exists") {
+                StatusCode::CONFLICT
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "TEMPLATE_CREATION_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn list_tenant_templates(
+    State(service): State<TenantServiceState>,
+    Query(params): Query<ListTemplatesQuery>,
+) -> Result<Json<Vec<TenantTemplate>>, (StatusCode, Json<serde_json::Value>)> {
+    match service.list_templates(params.include_inactive.unwrap_or(false)).await {
+        Ok(templates) => Ok(Json(templates)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "INTERNAL_ERROR",
+                    "message": e.to_string()
+                }
+            })),
+        )),
+    }
+}
+
+pub async fn get_tenant_template(
+    State(service): State<TenantServiceState>,
+    Path(name): Path<String>,
+) -> Result<Json<TenantTemplate>, (StatusCode, Json<serde_json::Value>)> {
+    match service.get_template(&name).await {
+        Ok(Some(template)) => Ok(Json(template)),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "TEMPLATE_NOT_FOUND",
+                    "message": "Tenant template not found"
+                }
+            })),
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "INTERNAL_ERROR",
+                    "message": e.to_string()
+                }
+            })),
+        )),
+    }
+}
+
+pub async fn update_tenant_template(
+    State(service): State<TenantServiceState>,
+    Path(name): Path<String>,
+    Json(request): Json<UpdateTenantTemplateRequest>,
+) -> Result<Json<TenantTemplate>, (StatusCode, Json<serde_json::Value>)> {
+    match service.update_template(&name, request).await {
+        Ok(template) => Ok(Json(template)),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "TEMPLATE_UPDATE_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn delete_tenant_template(
+    State(service): State<TenantServiceState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    match service.delete_template(&name).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else if e.to_string().contains("cannot be deleted") {
+                StatusCode::CONFLICT
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "TEMPLATE_DELETE_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+// Feature flag handlers
+#[derive(Debug, Deserialize)]
+pub struct ListFeatureFlagsQuery {
+    pub include_inactive: Option<bool>,
+}
+
+pub async fn create_feature_flag(
+    State(service): State<TenantServiceState>,
+    Json(request): Json<CreateFeatureFlagRequest>,
+) -> Result<(StatusCode, Json<FeatureFlag>), (StatusCode, Json<serde_json::Value>)> {
+    match service.create_feature_flag(request).await {
+        Ok(flag) => Ok((StatusCode::CREATED, Json(flag))),
+        Err(e) => {
+            let status = if e.to_string().contains("already exists") {
+                StatusCode::CONFLICT
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "FEATURE_FLAG_CREATION_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn list_feature_flags(
+    State(service): State<TenantServiceState>,
+    Query(params): Query<ListFeatureFlagsQuery>,
+) -> Result<Json<Vec<FeatureFlag>>, (StatusCode, Json<serde_json::Value>)> {
+    match service.list_feature_flags(params.include_inactive.unwrap_or(false)).await {
+        Ok(flags) => Ok(Json(flags)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "INTERNAL_ERROR",
+                    "message": e.to_string()
+                }
+            })),
+        )),
+    }
+}
+
+pub async fn get_feature_flag(
+    State(service): State<TenantServiceState>,
+    Path(key): Path<String>,
+) -> Result<Json<FeatureFlag>, (StatusCode, Json<serde_json::Value>)> {
+    match service.get_feature_flag(&key).await {
+        Ok(Some(flag)) => Ok(Json(flag)),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "FEATURE_FLAG_NOT_FOUND",
+                    "message": "Feature flag not found"
+                }
+            })),
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "INTERNAL_ERROR",
+                    "message": e.to_string()
+                }
+            })),
+        )),
+    }
+}
+
+pub async fn update_feature_flag(
+    State(service): State<TenantServiceState>,
+    Path(key): Path<String>,
+    Json(request): Json<UpdateFeatureFlagRequest>,
+) -> Result<Json<FeatureFlag>, (StatusCode, Json<serde_json::Value>)> {
+    match service.update_feature_flag(&key, request).await {
+        Ok(flag) => Ok(Json(flag)),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "FEATURE_FLAG_UPDATE_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn delete_feature_flag(
+    State(service): State<TenantServiceState>,
+    Path(key): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    match service.delete_feature_flag(&key).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "FEATURE_FLAG_DELETE_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn get_tenant_feature_flags(
+    State(service): State<TenantServiceState>,
+    Path(id): Path<TenantId>,
+) -> Result<Json<FeatureFlagSet>, (StatusCode, Json<serde_json::Value>)> {
+    match service.evaluate_feature_flags(&id).await {
+        Ok(flags) => Ok(Json(flags)),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "FEATURE_FLAG_EVALUATION_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn explain_tenant_feature_flags(
+    State(service): State<TenantServiceState>,
+    Path(id): Path<TenantId>,
+) -> Result<Json<Vec<FlagEvaluation>>, (StatusCode, Json<serde_json::Value>)> {
+    match service.explain_feature_flags(&id).await {
+        Ok(evaluations) => Ok(Json(evaluations)),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "FEATURE_FLAG_EVALUATION_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn set_tenant_flag_override(
+    State(service): State<TenantServiceState>,
+    Path((id, key)): Path<(TenantId, String)>,
+    Json(request): Json<SetFlagOverrideRequest>,
+) -> Result<Json<TenantFlagOverride>, (StatusCode, Json<serde_json::Value>)> {
+    match service.set_flag_override(&id, &key, request).await {
+        Ok(tenant_override) => Ok(Json(tenant_override)),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "FEATURE_FLAG_OVERRIDE_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn delete_tenant_flag_override(
+    State(service): State<TenantServiceState>,
+    Path((id, key)): Path<(TenantId, String)>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    match service.delete_flag_override(&id, &key).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "FEATURE_FLAG_OVERRIDE_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+// Tenant configuration handlers
+#[derive(Debug, Deserialize)]
+pub struct ResetTenantConfigQuery {
+    pub changed_by: Option<UserId>,
+}
+
+pub async fn create_config_namespace(
+    State(service): State<TenantServiceState>,
+    Json(request): Json<CreateConfigNamespaceRequest>,
+) -> Result<(StatusCode, Json<ConfigNamespace>), (StatusCode, Json<serde_json::Value>)> {
+    match service.create_config_namespace(request).await {
+        Ok(namespace) => Ok((StatusCode::CREATED, Json(namespace))),
+        Err(e) => {
+            let status = if e.to_string().contains("already exists") {
+                StatusCode::CONFLICT
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "CONFIG_NAMESPACE_CREATION_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn list_config_namespaces(
+    State(service): State<TenantServiceState>,
+) -> Result<Json<Vec<ConfigNamespace>>, (StatusCode, Json<serde_json::Value>)> {
+    match service.list_config_namespaces().await {
+        Ok(namespaces) => Ok(Json(namespaces)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "INTERNAL_ERROR",
+                    "message": e.to_string()
+                }
+            })),
+        )),
+    }
+}
+
+pub async fn get_config_namespace(
+    State(service): State<TenantServiceState>,
+    Path(name): Path<String>,
+) -> Result<Json<ConfigNamespace>, (StatusCode, Json<serde_json::Value>)> {
+    match service.get_config_namespace(&name).await {
+        Ok(Some(namespace)) => Ok(Json(namespace)),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "CONFIG_NAMESPACE_NOT_FOUND",
+                    "message": "Config namespace not found"
+                }
+            })),
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "INTERNAL_ERROR",
+                    "message": e.to_string()
+                }
+            })),
+        )),
+    }
+}
+
+pub async fn update_config_namespace(
+    State(service): State<TenantServiceState>,
+    Path(name): Path<String>,
+    Json(request): Json<UpdateConfigNamespaceRequest>,
+) -> Result<Json<ConfigNamespace>, (StatusCode, Json<serde_json::Value>)> {
+    match service.update_config_namespace(&name, request).await {
+        Ok(namespace) => Ok(Json(namespace)),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "CONFIG_NAMESPACE_UPDATE_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn delete_config_namespace(
+    State(service): State<TenantServiceState>,
+    Path(name): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    match service.delete_config_namespace(&name).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else if e.to_string().contains("cannot be deleted") {
+                StatusCode::BAD_REQUEST
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "CONFIG_NAMESPACE_DELETE_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn list_tenant_config(
+    State(service): State<TenantServiceState>,
+    Path(id): Path<TenantId>,
+) -> Result<Json<Vec<ResolvedTenantConfig>>, (StatusCode, Json<serde_json::Value>)> {
+    match service.list_tenant_config(&id).await {
+        Ok(config) => Ok(Json(config)),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "TENANT_CONFIG_FETCH_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn get_tenant_config(
+    State(service): State<TenantServiceState>,
+    Path((id, namespace)): Path<(TenantId, String)>,
+) -> Result<Json<ResolvedTenantConfig>, (StatusCode, Json<serde_json::Value>)> {
+    match service.get_tenant_config(&id, &namespace).await {
+        Ok(config) => Ok(Json(config)),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "TENANT_CONFIG_FETCH_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn set_tenant_config(
+    State(service): State<TenantServiceState>,
+    Path((id, namespace)): Path<(TenantId, String)>,
+    Json(request): Json<SetTenantConfigRequest>,
+) -> Result<Json<ResolvedTenantConfig>, (StatusCode, Json<serde_json::Value>)> {
+    match service.set_tenant_config(&id, &namespace, request).await {
+        Ok(config) => Ok(Json(config)),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else if e.to_string().contains("version conflict") {
+                StatusCode::CONFLICT
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "TENANT_CONFIG_UPDATE_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn reset_tenant_config(
+    State(service): State<TenantServiceState>,
+    Path((id, namespace)): Path<(TenantId, String)>,
+    Query(params): Query<ResetTenantConfigQuery>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    match service.reset_tenant_config(&id, &namespace, params.changed_by).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "TENANT_CONFIG_RESET_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+// Lifecycle event and webhook handlers
+#[derive(Debug, Deserialize)]
+pub struct ListLifecycleEventsQuery {
+    /// Sequence of the last event the caller has seen
+    pub after: Option<u64>,
+    pub tenant_id: Option<TenantId>,
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct LifecycleEventStreamQuery {
+    pub tenant_id: Option<TenantId>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ListWebhookDeliveriesQuery {
+    pub limit: Option<u32>,
+}
+
+pub async fn list_lifecycle_events(
+    State(service): State<TenantServiceState>,
+    Query(params): Query<ListLifecycleEventsQuery>,
+) -> Result<Json<Vec<TenantLifecycleEvent>>, (StatusCode, Json<serde_json::Value>)> {
+    let limit = params.limit.unwrap_or(DEFAULT_EVENT_PAGE_SIZE);
+    match service.list_lifecycle_events(params.after.unwrap_or(0), params.tenant_id.as_ref(), limit).await {
+        Ok(events) => Ok(Json(events)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "INTERNAL_ERROR",
+                    "message": e.to_string()
+                }
+            })),
+        )),
+    }
+}
+
+/// Server-sent stream of lifecycle events as they happen. Each event's id is
+/// its sequence, so a client that falls behind (a "lagged" event) or
+/// reconnects can catch up through the paged event list.
+pub async fn stream_lifecycle_events(
+    State(service): State<TenantServiceState>,
+    Query(params): Query<LifecycleEventStreamQuery>,
+) -> Sse<impl Stream<Item = Result<Event, Infallible>>> {
+    let receiver = service.subscribe_lifecycle_events();
+    let tenant_id = params.tenant_id;
+
+    let events = stream::unfold(receiver, move |mut receiver| {
+        let tenant_id = tenant_id.clone();
+        async move {
+            loop {
+                match receiver.recv().await {
+                    Ok(event) => {
+                        if matches!(&tenant_id, Some(id) if id != &event.tenant_id) {
+                            continue;
+                        }
+                        let sse_event = Event::default()
+                            .id(event.sequence.to_string())
+                            .event(event.event_type.as_str())
+                            .json_data(&event)
+                            .unwrap_or_else(|_| Event::default().comment("unserializable event"));
+                        return Some((Ok(sse_event), receiver));
+                    }
+                    Err(RecvError::Lagged(skipped)) => {
+                        let sse_event = Event::default().event("lagged").data(skipped.to_string());
+                        return Some((Ok(sse_event), receiver));
+                    }
+                    Err(RecvError::Closed) => return None,
+                }
+            }
+        }
+    });
+
+    Sse::new(events).keep_alive(KeepAlive::default())
+}
+
+pub async fn create_webhook(
+    State(service): State<TenantServiceState>,
+    Json(request): Json<CreateTenantWebhookRequest>,
+) -> Result<(StatusCode, Json<CreateTenantWebhookResponse>), (StatusCode, Json<serde_json::Value>)> {
+    match service.create_webhook(request).await {
+        Ok(response) => Ok((StatusCode::CREATED, Json(response))),
+        Err(e) => Err((
+            StatusCode::BAD_REQUEST,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "WEBHOOK_CREATION_FAILED",
+                    "message": e.to_string()
+                }
+            })),
+        )),
+    }
+}
+
+pub async fn list_webhooks(
+    State(service): State<TenantServiceState>,
+) -> Result<Json<Vec<TenantWebhook>>, (StatusCode, Json<serde_json::Value>)> {
+    match service.list_webhooks().await {
+        Ok(webhooks) => Ok(Json(webhooks)),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "INTERNAL_ERROR",
+                    "message": e.to_string()
+                }
+            })),
+        )),
+    }
+}
+
+pub async fn get_webhook(
+    State(service): State<TenantServiceState>,
+    Path(id): Path<String>,
+) -> Result<Json<TenantWebhook>, (StatusCode, Json<serde_json::Value>)> {
+    match service.get_webhook(&id).await {
+        Ok(Some(webhook)) => Ok(Json(webhook)),
+        Ok(None) => Err((
+            StatusCode::NOT_FOUND,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "WEBHOOK_NOT_FOUND",
+                    "message": "Webhook not found"
+                }
+            })),
+        )),
+        Err(e) => Err((
+            StatusCode::INTERNAL_SERVER_ERROR,
+            Json(serde_json::json!({
+                "error": {
+                    "code": "INTERNAL_ERROR",
+                    "message": e.to_string()
+                }
+            })),
+        )),
+    }
+}
+
+pub async fn update_webhook(
+    State(service): State<TenantServiceState>,
+    Path(id): Path<String>,
+    Json(request): Json<UpdateTenantWebhookRequest>,
+) -> Result<Json<TenantWebhook>, (StatusCode, Json<serde_json::Value>)> {
+    match service.update_webhook(&id, request).await {
+        Ok(webhook) => Ok(Json(webhook)),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "WEBHOOK_UPDATE_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn rotate_webhook_secret(
+    State(service): State<TenantServiceState>,
+    Path(id): Path<String>,
+    Json(request): Json<RotateTenantWebhookSecretRequest>,
+) -> Result<Json<RotateTenantWebhookSecretResponse>, (StatusCode, Json<serde_json::Value>)> {
+    match service.rotate_webhook_secret(&id, request).await {
+        Ok(response) => Ok(Json(response)),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::BAD_REQUEST
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "WEBHOOK_SECRET_ROTATION_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn delete_webhook(
+    State(service): State<TenantServiceState>,
+    Path(id): Path<String>,
+) -> Result<StatusCode, (StatusCode, Json<serde_json::Value>)> {
+    match service.delete_webhook(&id).await {
+        Ok(_) => Ok(StatusCode::NO_CONTENT),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "WEBHOOK_DELETION_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
+pub async fn list_webhook_deliveries(
+    State(service): State<TenantServiceState>,
+    Path(id): Path<String>,
+    Query(params): Query<ListWebhookDeliveriesQuery>,
+) -> Result<Json<Vec<WebhookDelivery>>, (StatusCode, Json<serde_json::Value>)> {
+    match service.list_webhook_deliveries(&id, params.limit.unwrap_or(DEFAULT_EVENT_PAGE_SIZE)).await {
+        Ok(deliveries) => Ok(Json(deliveries)),
+        Err(e) => {
+            let status = if e.to_string().contains("not found") {
+                StatusCode::NOT_FOUND
+            } else {
+                StatusCode::INTERNAL_SERVER_ERROR
+            };
+
+            Err((
+                status,
+                Json(serde_json::json!({
+                    "error": {
+                        "code": "WEBHOOK_DELIVERIES_FAILED",
+                        "message": e.to_string()
+                    }
+                })),
+            ))
+        }
+    }
+}
+
 // Membership handlers
 pub async fn create_membership(
     State(service): State<TenantServiceState>,
@@ -0,0 +1,148 @@
+use serde::{Deserialize, Serialize};
+
+use crate::models::*;
+use adx_shared::types::{TenantId, TenantQuotas};
+
+// Sub-tenant hierarchy rules. A parent's quotas cover its whole subtree:
+// each child is allocated a slice of the parent's limits, and the sum of
+// child allocations may never exceed what the parent has.
+
+/// Deepest allowed chain of parent links below a root tenant
+pub const MAX_HIERARCHY_DEPTH: usize = 5;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantHierarchyNode {
+    pub tenant_id: TenantId,
+    pub name: String,
+    pub slug: String,
+    pub status: TenantStatus,
+    pub inherit_settings: bool,
+    pub quotas: TenantQuotas,
+    /// Sum of the quotas handed out to direct children
+    pub allocated_to_children: TenantQuotas,
+    pub children: Vec<TenantHierarchyNode>,
+}
+
+fn sum_limit(mut values: impl Iterator<Item = Option<u32>>) -> Option<u32> {
+    // An unlimited child makes the roll-up unlimited
+    values.try_fold(0u32, |total, value| value.map(|v| total.saturating_add(v)))
+}
+
+/// Total quota allocated across a set of sub-tenants
+pub fn rollup_quotas(children: &[TenantQuotas]) -> TenantQuotas {
+    TenantQuotas {
+        max_users: sum_limit(children.iter().map(|q| q.max_users)),
+        max_storage_gb: sum_limit(children.iter().map(|q| q.max_storage_gb)),
+        max_api_calls_per_hour: sum_limit(children.iter().map(|q| q.max_api_calls_per_hour)),
+        max_workflows_per_hour: sum_limit(children.iter().map(|q| q.max_workflows_per_hour)),
+    }
+}
+
+fn check_limit(name: &str, parent: Option<u32>, allocated: u32, requested: Option<u32>, errors: &mut Vec<String>) {
+    let Some(parent_limit) = parent else {
+        return;
+    };
+
+    match requested {
+        None => errors.push(format!("{} cannot be unlimited under a parent limited to {}", name, parent_limit)),
+        Some(requested) if allocated.saturating_add(requested) > parent_limit => errors.push(format!(
+            "{} of {} exceeds the parent's remaining allocation of {}",
+            name,
+            requested,
+            parent_limit.saturating_sub(allocated)
+        )),
+        Some(_) => {}
+    }
+}
+
+/// Check that a new or resized child allocation fits within the parent's
+/// remaining quota. `siblings` must not include the child being checked.
+pub fn validate_child_quotas(
+    parent: &TenantQuotas,
+    siblings: &[TenantQuotas],
+    requested: &TenantQuotas,
+) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+
+    check_limit("max_users", parent.max_users, sum_all(siblings, |q| q.max_users), requested.max_users, &mut errors);
+    check_limit("max_storage_gb", parent.max_storage_gb, sum_all(siblings, |q| q.max_storage_gb), requested.max_storage_gb, &mut errors);
+    check_limit("max_api_calls_per_hour", parent.max_api_calls_per_hour, sum_all(siblings, |q| q.max_api_calls_per_hour), requested.max_api_calls_per_hour, &mut errors);
+    check_limit("max_workflows_per_hour", parent.max_workflows_per_hour, sum_all(siblings, |q| q.max_workflows_per_hour), requested.max_workflows_per_hour, &mut errors);
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+fn sum_all(quotas: &[TenantQuotas], field: impl Fn(&TenantQuotas) -> Option<u32>) -> u32 {
+    quotas.iter().filter_map(field).fold(0u32, |total, v| total.saturating_add(v))
+}
+
+/// Settings a tenant actually runs with: its own, or those of the nearest
+/// ancestor that does not inherit. `ancestors` is ordered nearest first.
+pub fn effective_settings(tenant: &Tenant, ancestors: &[Tenant]) -> TenantSettings {
+    if !tenant.inherit_settings {
+        return tenant.settings.clone();
+    }
+
+    ancestors
+        .iter()
+        .find(|ancestor| !ancestor.inherit_settings)
+        .or(ancestors.last())
+        .map(|ancestor| ancestor.settings.clone())
+        .unwrap_or_else(|| tenant.settings.clone())
+}
+
+/// Admin roles on an ancestor carry over to every tenant beneath it
+pub fn grants_descendant_admin(role: &TenantRole) -> bool {
+    matches!(role, TenantRole::Owner | TenantRole::Admin)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn quotas(users: Option<u32>, storage: Option<u32>) -> TenantQuotas {
+        TenantQuotas {
+            max_users: users,
+            max_storage_gb: storage,
+            max_api_calls_per_hour: None,
+            max_workflows_per_hour: None,
+        }
+    }
+
+    #[test]
+    fn test_rollup_sums_and_propagates_unlimited() {
+        let rolled = rollup_quotas(&[quotas(Some(5), Some(10)), quotas(Some(3), None)]);
+        assert_eq!(rolled.max_users, Some(8));
+        assert_eq!(rolled.max_storage_gb, None);
+
+        let empty = rollup_quotas(&[]);
+        assert_eq!(empty.max_users, Some(0));
+    }
+
+    #[test]
+    fn test_child_allocation_must_fit_parent() {
+        let parent = quotas(Some(10), None);
+        let siblings = vec![quotas(Some(6), Some(50))];
+
+        assert!(validate_child_quotas(&parent, &siblings, &quotas(Some(4), Some(100))).is_ok());
+
+        let errors = validate_child_quotas(&parent, &siblings, &quotas(Some(5), None)).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("remaining allocation of 4"));
+
+        let errors = validate_child_quotas(&parent, &[], &quotas(None, None)).unwrap_err();
+        assert!(errors[0].contains("cannot be unlimited"));
+    }
+
+    #[test]
+    fn test_descendant_admin_roles() {
+        assert!(grants_descendant_admin(&TenantRole::Owner));
+        assert!(grants_descendant_admin(&TenantRole::Admin));
+        assert!(!grants_descendant_admin(&TenantRole::Member));
+        assert!(!grants_descendant_admin(&TenantRole::Guest));
+    }
+}
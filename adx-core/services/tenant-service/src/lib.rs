@@ -1,10 +1,22 @@
+pub mod cloning;
+pub mod cold_storage;
+pub mod deletion;
+pub mod export;
+pub mod feature_flags;
+pub mod grpc;
 pub mod handlers;
+pub mod hierarchy;
+pub mod lifecycle_events;
 pub mod models;
+pub mod region_migration;
 pub mod repository_traits;
-// pub mod repositories; // Commented out due to SQLx compilation issues
+pub mod repositories;
 pub mod repositories_mock;
 pub mod repositories_simple;
 pub mod services;
+pub mod templates;
+pub mod tenant_config;
+pub mod trial;
 pub mod activities;
 pub mod workflows;
 pub mod server;
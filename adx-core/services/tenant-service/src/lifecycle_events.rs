@@ -0,0 +1,419 @@
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use serde::{Deserialize, Serialize};
+use sha2::Sha256;
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::models::*;
+use crate::repository_traits::LifecycleEventRepository;
+use adx_shared::types::{SubscriptionTier, TenantId};
+
+// Tenant lifecycle events. Every event is appended to an ordered log that
+// platform admins can page through or stream, and is pushed to each active
+// webhook subscribed to its type. Deliveries are signed with the webhook's
+// secret and retried with backoff.
+
+pub const WEBHOOK_SIGNATURE_HEADER: &str = "X-ADX-Signature";
+pub const WEBHOOK_TIMESTAMP_HEADER: &str = "X-ADX-Timestamp";
+pub const WEBHOOK_EVENT_HEADER: &str = "X-ADX-Event";
+
+pub const DEFAULT_EVENT_PAGE_SIZE: u32 = 100;
+pub const MAX_EVENT_PAGE_SIZE: u32 = 1000;
+
+pub const MAX_DELIVERY_ATTEMPTS: u32 = 5;
+
+/// How long a rotated-out secret keeps signing deliveries by default
+pub const DEFAULT_SECRET_OVERLAP_HOURS: u32 = 24;
+pub const MAX_SECRET_OVERLAP_HOURS: u32 = 24 * 14;
+const DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum TenantLifecycleEventType {
+    #[serde(rename = "tenant.created")]
+    Created,
+    #[serde(rename = "tenant.suspended")]
+    Suspended,
+    #[serde(rename = "tenant.reactivated")]
+    Reactivated,
+    #[serde(rename = "tenant.upgraded")]
+    Upgraded,
+    #[serde(rename = "tenant.downgraded")]
+    Downgraded,
+    #[serde(rename = "tenant.archived")]
+    Archived,
+    #[serde(rename = "tenant.restored")]
+    Restored,
+    #[serde(rename = "tenant.deletion_scheduled")]
+    DeletionScheduled,
+    #[serde(rename = "tenant.deletion_cancelled")]
+    DeletionCancelled,
+    #[serde(rename = "tenant.terminated")]
+    Terminated,
+}
+
+impl TenantLifecycleEventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Created => "tenant.created",
+            Self::Suspended => "tenant.suspended",
+            Self::Reactivated => "tenant.reactivated",
+            Self::Upgraded => "tenant.upgraded",
+            Self::Downgraded => "tenant.downgraded",
+            Self::Archived => "tenant.archived",
+            Self::Restored => "tenant.restored",
+            Self::DeletionScheduled => "tenant.deletion_scheduled",
+            Self::DeletionCancelled => "tenant.deletion_cancelled",
+            Self::Terminated => "tenant.terminated",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantLifecycleEvent {
+    pub event_id: String,
+    /// Position in the event log; assigned when the event is appended
+    pub sequence: u64,
+    pub event_type: TenantLifecycleEventType,
+    pub tenant_id: TenantId,
+    pub tenant_name: String,
+    pub subscription_tier: SubscriptionTier,
+    pub status: TenantStatus,
+    /// Event-specific details, e.g. the previous tier for upgrades
+    pub data: serde_json::Value,
+    pub occurred_at: DateTime<Utc>,
+}
+
+impl TenantLifecycleEvent {
+    pub fn new(event_type: TenantLifecycleEventType, tenant: &Tenant, data: serde_json::Value) -> Self {
+        Self {
+            event_id: uuid::Uuid::new_v4().to_string(),
+            sequence: 0,
+            event_type,
+            tenant_id: tenant.id.clone(),
+            tenant_name: tenant.name.clone(),
+            subscription_tier: tenant.subscription_tier.clone(),
+            status: tenant.status.clone(),
+            data,
+            occurred_at: Utc::now(),
+        }
+    }
+}
+
+fn tier_rank(tier: &SubscriptionTier) -> u8 {
+    match tier {
+        SubscriptionTier::Free => 0,
+        SubscriptionTier::Professional => 1,
+        SubscriptionTier::Enterprise => 2,
+        SubscriptionTier::Custom => 3,
+    }
+}
+
+/// Events implied by a tenant update, comparing the stored tenant with the updated one
+pub fn events_for_update(before: &Tenant, after: &Tenant) -> Vec<TenantLifecycleEvent> {
+    let mut events = Vec::new();
+
+    if before.subscription_tier != after.subscription_tier {
+        let event_type = if tier_rank(&after.subscription_tier) > tier_rank(&before.subscription_tier) {
+            TenantLifecycleEventType::Upgraded
+        } else {
+            TenantLifecycleEventType::Downgraded
+        };
+        events.push(TenantLifecycleEvent::new(
+            event_type,
+            after,
+            serde_json::json!({ "previous_tier": before.subscription_tier }),
+        ));
+    }
+
+    if before.status != after.status {
+        let event_type = match (&before.status, &after.status) {
+            (_, TenantStatus::Suspended) => Some(TenantLifecycleEventType::Suspended),
+            (TenantStatus::Suspended, TenantStatus::Active) => Some(TenantLifecycleEventType::Reactivated),
+            _ => None,
+        };
+        if let Some(event_type) = event_type {
+            events.push(TenantLifecycleEvent::new(
+                event_type,
+                after,
+                serde_json::json!({ "previous_status": before.status }),
+            ));
+        }
+    }
+
+    events
+}
+
+pub fn validate_webhook_url(url: &str) -> Result<(), String> {
+    let local = url.starts_with("http://localhost") || url.starts_with("http://127.0.0.1");
+    if !url.starts_with("https://") && !local {
+        return Err("Webhook URL must use https".to_string());
+    }
+    Ok(())
+}
+
+/// Whether a webhook wants an event; an empty type list subscribes to everything
+pub fn webhook_accepts(webhook: &TenantWebhook, event_type: TenantLifecycleEventType) -> bool {
+    webhook.is_active && (webhook.event_types.is_empty() || webhook.event_types.contains(&event_type))
+}
+
+/// HMAC-SHA256 over "{timestamp}.{body}", hex encoded. Including the timestamp
+/// lets receivers reject replayed deliveries.
+pub fn sign_payload(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={:x}", mac.finalize().into_bytes())
+}
+
+/// Secrets a delivery is signed with: the current one, plus the previous one
+/// while its overlap window is open
+pub fn signing_secrets(webhook: &TenantWebhook, now: DateTime<Utc>) -> Vec<&str> {
+    let mut secrets = vec![webhook.secret.as_str()];
+    if let (Some(previous), Some(expires_at)) = (&webhook.previous_secret, webhook.previous_secret_expires_at) {
+        if now < expires_at {
+            secrets.push(previous.as_str());
+        }
+    }
+    secrets
+}
+
+/// Signature header value with one comma-separated signature per valid
+/// secret, so receivers still holding the old secret keep verifying during a
+/// rotation
+pub fn signature_header(webhook: &TenantWebhook, timestamp: i64, body: &[u8], now: DateTime<Utc>) -> String {
+    signing_secrets(webhook, now)
+        .into_iter()
+        .map(|secret| sign_payload(secret, timestamp, body))
+        .collect::<Vec<_>>()
+        .join(",")
+}
+
+/// Backoff before the given retry (1-based): 1s, 4s, 16s, ...
+pub fn retry_delay(attempt: u32) -> Duration {
+    Duration::from_secs(4u64.saturating_pow(attempt.saturating_sub(1)))
+}
+
+/// Pushes lifecycle events to webhooks in the background
+pub struct WebhookDispatcher {
+    client: reqwest::Client,
+    event_repo: Arc<dyn LifecycleEventRepository>,
+}
+
+impl WebhookDispatcher {
+    pub fn new(event_repo: Arc<dyn LifecycleEventRepository>) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(DELIVERY_TIMEOUT)
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+
+        Self { client, event_repo }
+    }
+
+    /// Start delivering an event to every webhook that accepts it
+    pub async fn dispatch(&self, event: &TenantLifecycleEvent) -> anyhow::Result<usize> {
+        let webhooks: Vec<TenantWebhook> = self.event_repo.list_webhooks().await?
+            .into_iter()
+            .filter(|webhook| webhook_accepts(webhook, event.event_type))
+            .collect();
+
+        for webhook in &webhooks {
+            let client = self.client.clone();
+            let event_repo = self.event_repo.clone();
+            let webhook = webhook.clone();
+            let event = event.clone();
+            tokio::spawn(async move {
+                deliver_with_retries(&client, event_repo.as_ref(), &webhook, &event).await;
+            });
+        }
+
+        Ok(webhooks.len())
+    }
+}
+
+async fn deliver_with_retries(
+    client: &reqwest::Client,
+    event_repo: &dyn LifecycleEventRepository,
+    webhook: &TenantWebhook,
+    event: &TenantLifecycleEvent,
+) {
+    let body = match serde_json::to_vec(event) {
+        Ok(body) => body,
+        Err(e) => {
+            tracing::error!("Failed to serialize lifecycle event {}: {}", event.event_id, e);
+            return;
+        }
+    };
+
+    for attempt in 1..=MAX_DELIVERY_ATTEMPTS {
+        if attempt > 1 {
+            tokio::time::sleep(retry_delay(attempt - 1)).await;
+        }
+
+        let now = Utc::now();
+        let timestamp = now.timestamp();
+        let result = client
+            .post(&webhook.url)
+            .header("Content-Type", "application/json")
+            .header(WEBHOOK_EVENT_HEADER, event.event_type.as_str())
+            .header(WEBHOOK_TIMESTAMP_HEADER, timestamp.to_string())
+            .header(WEBHOOK_SIGNATURE_HEADER, signature_header(webhook, timestamp, &body, now))
+            .body(body.clone())
+            .send()
+            .await;
+
+        let (status_code, error) = match result {
+            Ok(response) if response.status().is_success() => (Some(response.status().as_u16()), None),
+            Ok(response) => (Some(response.status().as_u16()), Some(format!("Receiver returned {}", response.status()))),
+            Err(e) => (None, Some(e.to_string())),
+        };
+        let success = error.is_none();
+
+        let delivery = WebhookDelivery {
+            id: uuid::Uuid::new_v4().to_string(),
+            webhook_id: webhook.id.clone(),
+            event_id: event.event_id.clone(),
+            event_type: event.event_type,
+            attempt,
+            status_code,
+            success,
+            error,
+            attempted_at: Utc::now(),
+        };
+        if let Err(e) = event_repo.record_delivery(&delivery).await {
+            tracing::error!("Failed to record webhook delivery for {}: {}", webhook.id, e);
+        }
+
+        if success {
+            return;
+        }
+    }
+
+    tracing::warn!(
+        "Giving up on webhook {} for event {} after {} attempts",
+        webhook.id,
+        event.event_id,
+        MAX_DELIVERY_ATTEMPTS
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use adx_shared::types::{TenantIsolationLevel, TenantQuotas};
+
+    fn tenant(tier: SubscriptionTier, status: TenantStatus) -> Tenant {
+        Tenant {
+            id: "tenant-1".to_string(),
+            name: "Acme".to_string(),
+            slug: "acme".to_string(),
+            admin_email: "admin@acme.test".to_string(),
+            subscription_tier: tier,
+            isolation_level: TenantIsolationLevel::Schema,
+            quotas: TenantQuotas::default(),
+            features: vec![],
+            settings: TenantSettings::default(),
+            status,
+            parent_tenant_id: None,
+            inherit_settings: false,
+            region: None,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        }
+    }
+
+    #[test]
+    fn test_update_events() {
+        let before = tenant(SubscriptionTier::Professional, TenantStatus::Active);
+
+        let upgraded = tenant(SubscriptionTier::Enterprise, TenantStatus::Active);
+        let events = events_for_update(&before, &upgraded);
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].event_type, TenantLifecycleEventType::Upgraded);
+        assert_eq!(events[0].data["previous_tier"], "Professional");
+
+        let suspended = tenant(SubscriptionTier::Free, TenantStatus::Suspended);
+        let types: Vec<_> = events_for_update(&before, &suspended).into_iter().map(|e| e.event_type).collect();
+        assert_eq!(types, vec![TenantLifecycleEventType::Downgraded, TenantLifecycleEventType::Suspended]);
+
+        let reactivated = events_for_update(&suspended, &tenant(SubscriptionTier::Free, TenantStatus::Active));
+        assert_eq!(reactivated[0].event_type, TenantLifecycleEventType::Reactivated);
+
+        assert!(events_for_update(&before, &before).is_empty());
+    }
+
+    #[test]
+    fn test_signature_covers_timestamp_and_body() {
+        let signature = sign_payload("secret", 1_700_000_000, b"{}");
+        assert!(signature.starts_with("sha256="));
+        assert_eq!(signature.len(), "sha256=".len() + 64);
+        assert_eq!(signature, sign_payload("secret", 1_700_000_000, b"{}"));
+        assert_ne!(signature, sign_payload("secret", 1_700_000_001, b"{}"));
+        assert_ne!(signature, sign_payload("other", 1_700_000_000, b"{}"));
+    }
+
+    #[test]
+    fn test_rotation_signs_with_both_secrets_until_expiry() {
+        let now = Utc::now();
+        let mut webhook = TenantWebhook {
+            id: "wh-1".to_string(),
+            url: "https://crm.example.com/hooks".to_string(),
+            secret: "new-secret".to_string(),
+            previous_secret: Some("old-secret".to_string()),
+            previous_secret_expires_at: Some(now + chrono::Duration::hours(1)),
+            secret_rotated_at: Some(now),
+            description: None,
+            event_types: vec![],
+            is_active: true,
+            created_at: now,
+            updated_at: now,
+        };
+
+        let header = signature_header(&webhook, 1_700_000_000, b"{}", now);
+        assert_eq!(
+            header,
+            format!(
+                "{},{}",
+                sign_payload("new-secret", 1_700_000_000, b"{}"),
+                sign_payload("old-secret", 1_700_000_000, b"{}")
+            )
+        );
+
+        let after_window = now + chrono::Duration::hours(2);
+        assert_eq!(signing_secrets(&webhook, after_window), vec!["new-secret"]);
+
+        webhook.previous_secret = None;
+        assert_eq!(signature_header(&webhook, 1_700_000_000, b"{}", now), sign_payload("new-secret", 1_700_000_000, b"{}"));
+    }
+
+    #[test]
+    fn test_webhook_filtering_and_urls() {
+        let mut webhook = TenantWebhook {
+            id: "wh-1".to_string(),
+            url: "https://crm.example.com/hooks".to_string(),
+            secret: "secret".to_string(),
+            previous_secret: None,
+            previous_secret_expires_at: None,
+            secret_rotated_at: None,
+            description: None,
+            event_types: vec![],
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+        assert!(webhook_accepts(&webhook, TenantLifecycleEventType::Created));
+
+        webhook.event_types = vec![TenantLifecycleEventType::Suspended];
+        assert!(!webhook_accepts(&webhook, TenantLifecycleEventType::Created));
+        assert!(webhook_accepts(&webhook, TenantLifecycleEventType::Suspended));
+
+        webhook.is_active = false;
+        assert!(!webhook_accepts(&webhook, TenantLifecycleEventType::Suspended));
+
+        assert!(validate_webhook_url("http://crm.example.com").is_err());
+        assert!(validate_webhook_url("http://localhost:9000/hook").is_ok());
+        assert_eq!(retry_delay(1), Duration::from_secs(1));
+        assert_eq!(retry_delay(3), Duration::from_secs(16));
+    }
+}
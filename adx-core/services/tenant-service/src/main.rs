@@ -1,6 +1,6 @@
 use clap::{Parser, Subcommand};
 use adx_shared::{config::AppConfig, logging::init_logging, database::create_connection_pool};
-use tenant_service::{server, worker};
+use tenant_service::{repositories, server, worker};
 
 #[derive(Parser)]
 #[command(name = "tenant-service")]
@@ -27,6 +27,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     
     // Create database connection pool
     let pool = create_connection_pool(&config.database).await?;
+    repositories::seed_builtin_records(&pool).await?;
     
     match cli.command.unwrap_or(Commands::Server) {
         Commands::Server => {
@@ -39,8 +40,19 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             tracing::info!("   • GET  /api/v1/tenants/:id - Get tenant");
             tracing::info!("   • PUT  /api/v1/tenants/:id - Update tenant");
             tracing::info!("   • DELETE /api/v1/tenants/:id - Delete tenant");
+            tracing::info!("   • GET  /api/v1/tenants/:id/isolation - Tenant isolation placement");
+            tracing::info!("   • GET  /api/v1/tenants/:id/archive - Cold storage archive and restore SLA");
+            tracing::info!("   • POST /api/v1/tenants/:id/trial/extend - Extend a running trial");
+            tracing::info!("   • POST /api/v1/tenants/:id/trial/convert - Convert a trial to a paid plan");
             tracing::info!("   • POST /api/v1/tenant/switch - Switch tenant context");
             tracing::info!("   • Membership management endpoints");
+            tracing::info!("   • /api/v1/tenant-templates - Provisioning templates");
+            tracing::info!("   • /api/v1/feature-flags - Feature flag definitions");
+            tracing::info!("   • /api/v1/tenants/:id/feature-flags - Evaluated flags and per-tenant overrides");
+            tracing::info!("   • /api/v1/config-namespaces - Tenant configuration schemas");
+            tracing::info!("   • /api/v1/tenants/:id/config - Schema-validated tenant settings");
+            tracing::info!("   • /api/v1/admin/tenant-events - Tenant lifecycle event log and stream");
+            tracing::info!("   • /api/v1/admin/tenant-webhooks - Lifecycle webhook subscriptions");
             server::start_server(config, pool).await?;
         }
         Commands::Worker => {
@@ -49,8 +61,17 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             tracing::info!("   • create_tenant_workflow - Complex tenant creation");
             tracing::info!("   • switch_tenant_workflow - Complex tenant switching");
             tracing::info!("   • migrate_tenant_workflow - Tenant migration");
+            tracing::info!("   • migrate_tenant_region_workflow - Cross-region data migration");
             tracing::info!("   • suspend_tenant_workflow - Tenant suspension");
-            tracing::info!("   • terminate_tenant_workflow - Tenant termination");
+            tracing::info!("   • terminate_tenant_workflow - Staged deletion with destruction certificate");
+            tracing::info!("   • tenant_trial_workflow - Trial reminders, expiry and suspension");
+            tracing::info!("   • clone_tenant_workflow - Sandbox tenant cloning");
+            tracing::info!("   • create_sub_tenant_workflow - Sub-tenant creation");
+            tracing::info!("   • detach_sub_tenant_workflow - Sub-tenant detachment");
+            tracing::info!("   • export_tenant_workflow - Tenant data export");
+            tracing::info!("   • import_tenant_workflow - Tenant data import");
+            tracing::info!("   • archive_tenant_workflow - Cold storage archival");
+            tracing::info!("   • restore_archived_tenant_workflow - Restore from cold storage");
             worker::start_worker(config, pool).await?;
         }
     }
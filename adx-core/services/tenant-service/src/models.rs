@@ -1,7 +1,12 @@
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
+use adx_shared::context_token::IssuedContextToken;
+use adx_shared::feature_flags::FeatureFlagSet;
 use adx_shared::types::{TenantId, UserId, SubscriptionTier, TenantIsolationLevel, TenantQuotas};
 
+use crate::lifecycle_events::TenantLifecycleEventType;
+use crate::trial::{TrialPhase, TrialSignal};
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct Tenant {
     pub id: TenantId,
@@ -14,6 +19,15 @@ pub struct Tenant {
     pub features: Vec<String>,
     pub settings: TenantSettings,
     pub status: TenantStatus,
+    /// Set for sub-tenants; quotas are allocated from the parent's
+    #[serde(default)]
+    pub parent_tenant_id: Option<TenantId>,
+    /// Use the nearest non-inheriting ancestor's settings instead of our own
+    #[serde(default)]
+    pub inherit_settings: bool,
+    /// Region holding the tenant's data; unset means the deployment's default region
+    #[serde(default)]
+    pub region: Option<String>,
     pub created_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
 }
@@ -121,6 +135,12 @@ pub enum TenantStatus {
     Suspended,
     Pending,
     Cancelled,
+    /// Readable but writes are rejected, e.g. during a region migration freeze
+    ReadOnly,
+    /// Data offloaded to cold storage; must be restored before use
+    Archived,
+    /// Soft-deleted and waiting out the retention period before the purge
+    PendingDeletion,
 }
 
 impl Default for TenantStatus {
@@ -172,6 +192,200 @@ impl Default for MembershipStatus {
     }
 }
 
+// Tenant provisioning templates
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantTemplate {
+    pub name: String,
+    pub display_name: String,
+    pub description: Option<String>,
+    pub subscription_tier: SubscriptionTier,
+    pub quotas: TenantQuotas,
+    pub features: Vec<String>,
+    pub default_roles: Vec<TemplateRole>,
+    pub enabled_modules: Vec<String>,
+    pub sample_data: Vec<String>,
+    pub settings: Option<TenantSettings>,
+    pub is_builtin: bool,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct TemplateRole {
+    pub name: String,
+    pub description: String,
+    pub permissions: Vec<String>,
+    pub is_default: bool,
+}
+
+// Feature flags
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct FeatureFlag {
+    pub key: String,
+    pub description: Option<String>,
+    /// Value when no override, plan, tier or rollout rule applies
+    pub default_enabled: bool,
+    /// Tiers that get the flag regardless of their plan features
+    pub enabled_tiers: Vec<SubscriptionTier>,
+    /// Share of the remaining tenants (0-100) that get the flag
+    pub rollout_percentage: u8,
+    /// Inactive flags are off for every tenant, overrides included
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantFlagOverride {
+    pub tenant_id: TenantId,
+    pub flag_key: String,
+    pub enabled: bool,
+    pub reason: Option<String>,
+    pub updated_by: Option<UserId>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Where an archived tenant's data lives in cold storage
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantArchiveRecord {
+    pub tenant_id: TenantId,
+    pub archive_id: String,
+    pub location: String,
+    pub storage_class: String,
+    pub compressed_size_bytes: u64,
+    pub uncompressed_size_bytes: u64,
+    pub checksum: String,
+    /// Status to return the tenant to when it is restored
+    pub previous_status: TenantStatus,
+    pub reason: Option<String>,
+    pub archived_by: Option<UserId>,
+    pub archived_at: DateTime<Utc>,
+    /// Most recent restore, if one has been requested
+    pub restore: Option<TenantRestoreRecord>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantRestoreRecord {
+    pub requested_at: DateTime<Utc>,
+    pub requested_by: Option<UserId>,
+    pub sla_deadline: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub sla_breached: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TenantDeletionStage {
+    /// Tenant is unavailable but its data is intact and the deletion can be cancelled
+    SoftDeleted,
+    Purging,
+    Purged,
+    Cancelled,
+}
+
+/// A tenant's staged deletion; kept after the purge as evidence of destruction
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantDeletionRecord {
+    pub tenant_id: TenantId,
+    pub tenant_name: String,
+    pub stage: TenantDeletionStage,
+    /// Status to return the tenant to if the deletion is cancelled
+    pub previous_status: TenantStatus,
+    pub requested_by: UserId,
+    pub reason: Option<String>,
+    pub soft_deleted_at: DateTime<Utc>,
+    /// End of the retention period
+    pub purge_after: DateTime<Utc>,
+    pub purged_services: Vec<ServicePurgeResult>,
+    pub purged_at: Option<DateTime<Utc>>,
+    /// Destruction certificate held by security-service
+    pub certificate_id: Option<String>,
+    pub cancelled_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ServicePurgeResult {
+    pub service: String,
+    pub records_deleted: u64,
+    pub purged_at: DateTime<Utc>,
+}
+
+// Namespaced tenant configuration
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConfigNamespace {
+    pub name: String,
+    pub description: Option<String>,
+    /// Service that reads this namespace
+    pub owner_service: String,
+    /// JSON schema every resolved tenant value must satisfy
+    pub schema: serde_json::Value,
+    pub default_value: serde_json::Value,
+    /// Bumped whenever the schema or defaults change
+    pub version: u32,
+    pub is_builtin: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Keys a tenant has set in a namespace; unset keys fall back to the defaults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantConfigValue {
+    pub tenant_id: TenantId,
+    pub namespace: String,
+    pub value: serde_json::Value,
+    /// Incremented on every write, for optimistic concurrency
+    pub version: u64,
+    pub updated_by: Option<UserId>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedTenantConfig {
+    pub tenant_id: TenantId,
+    pub namespace: String,
+    pub value: serde_json::Value,
+    /// 0 when the tenant has never written to the namespace
+    pub version: u64,
+    pub schema_version: u32,
+    pub is_default: bool,
+}
+
+// Lifecycle webhooks
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantWebhook {
+    pub id: String,
+    pub url: String,
+    /// Signing secret; only returned when the webhook is created
+    #[serde(skip_serializing)]
+    pub secret: String,
+    /// Secret replaced by the last rotation, still signed with until it expires
+    #[serde(skip_serializing)]
+    pub previous_secret: Option<String>,
+    pub previous_secret_expires_at: Option<DateTime<Utc>>,
+    pub secret_rotated_at: Option<DateTime<Utc>>,
+    pub description: Option<String>,
+    /// Lifecycle events to deliver; empty means all of them
+    pub event_types: Vec<TenantLifecycleEventType>,
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// One attempt to deliver an event to a webhook
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WebhookDelivery {
+    pub id: String,
+    pub webhook_id: String,
+    pub event_id: String,
+    pub event_type: TenantLifecycleEventType,
+    pub attempt: u32,
+    pub status_code: Option<u16>,
+    pub success: bool,
+    pub error: Option<String>,
+    pub attempted_at: DateTime<Utc>,
+}
+
 // Request/Response DTOs
 #[derive(Debug, Deserialize)]
 pub struct CreateTenantRequest {
@@ -181,6 +395,173 @@ pub struct CreateTenantRequest {
     pub isolation_level: Option<TenantIsolationLevel>,
     pub features: Option<Vec<String>>,
     pub settings: Option<TenantSettings>,
+    #[serde(default)]
+    pub quotas: Option<TenantQuotas>,
+    /// Provisioning template; defaults to the template for the subscription tier
+    #[serde(default)]
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateSubTenantRequest {
+    pub name: String,
+    pub admin_email: String,
+    /// Allocation carved out of the parent's quotas; defaults to the template quotas
+    pub quotas: Option<TenantQuotas>,
+    pub features: Option<Vec<String>>,
+    pub settings: Option<TenantSettings>,
+    #[serde(default = "default_true")]
+    pub inherit_settings: bool,
+    #[serde(default)]
+    pub template: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DetachSubTenantRequest {
+    /// Tier for the now independent tenant; keeps the current tier when unset
+    pub subscription_tier: Option<SubscriptionTier>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExtendTrialRequest {
+    pub days: u32,
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ConvertTrialRequest {
+    pub subscription_tier: SubscriptionTier,
+}
+
+/// Returned once a signal has been delivered to the running trial workflow
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TrialSignalAccepted {
+    pub tenant_id: TenantId,
+    pub workflow_id: String,
+    pub signal: TrialSignal,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTenantTemplateRequest {
+    pub name: String,
+    pub display_name: String,
+    pub description: Option<String>,
+    pub subscription_tier: SubscriptionTier,
+    pub quotas: TenantQuotas,
+    #[serde(default)]
+    pub features: Vec<String>,
+    #[serde(default)]
+    pub default_roles: Vec<TemplateRole>,
+    #[serde(default)]
+    pub enabled_modules: Vec<String>,
+    #[serde(default)]
+    pub sample_data: Vec<String>,
+    pub settings: Option<TenantSettings>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateTenantTemplateRequest {
+    pub display_name: Option<String>,
+    pub description: Option<String>,
+    pub subscription_tier: Option<SubscriptionTier>,
+    pub quotas: Option<TenantQuotas>,
+    pub features: Option<Vec<String>>,
+    pub default_roles: Option<Vec<TemplateRole>>,
+    pub enabled_modules: Option<Vec<String>>,
+    pub sample_data: Option<Vec<String>>,
+    pub settings: Option<TenantSettings>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateFeatureFlagRequest {
+    pub key: String,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub default_enabled: bool,
+    #[serde(default)]
+    pub enabled_tiers: Vec<SubscriptionTier>,
+    #[serde(default)]
+    pub rollout_percentage: u8,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateFeatureFlagRequest {
+    pub description: Option<String>,
+    pub default_enabled: Option<bool>,
+    pub enabled_tiers: Option<Vec<SubscriptionTier>>,
+    pub rollout_percentage: Option<u8>,
+    pub is_active: Option<bool>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetFlagOverrideRequest {
+    pub enabled: bool,
+    pub reason: Option<String>,
+    pub updated_by: Option<UserId>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateConfigNamespaceRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub owner_service: String,
+    pub schema: serde_json::Value,
+    pub default_value: serde_json::Value,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateConfigNamespaceRequest {
+    pub description: Option<String>,
+    pub schema: Option<serde_json::Value>,
+    pub default_value: Option<serde_json::Value>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct SetTenantConfigRequest {
+    /// Keys to store; merged over the namespace defaults when read
+    pub value: serde_json::Value,
+    /// Rejects the write if another change landed since this version was read
+    pub expected_version: Option<u64>,
+    pub updated_by: Option<UserId>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct CreateTenantWebhookRequest {
+    pub url: String,
+    /// Generated when not supplied
+    pub secret: Option<String>,
+    pub description: Option<String>,
+    #[serde(default)]
+    pub event_types: Vec<TenantLifecycleEventType>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct CreateTenantWebhookResponse {
+    pub webhook: TenantWebhook,
+    pub secret: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct RotateTenantWebhookSecretRequest {
+    /// Generated when not supplied
+    pub secret: Option<String>,
+    /// How long deliveries keep a signature for the old secret; defaults to 24
+    pub overlap_hours: Option<u32>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct RotateTenantWebhookSecretResponse {
+    pub webhook: TenantWebhook,
+    pub secret: String,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateTenantWebhookRequest {
+    pub url: Option<String>,
+    pub description: Option<String>,
+    pub event_types: Option<Vec<TenantLifecycleEventType>>,
+    pub is_active: Option<bool>,
 }
 
 #[derive(Debug, Deserialize)]
@@ -219,6 +600,8 @@ pub struct SwitchTenantResponse {
     pub new_tenant_id: TenantId,
     pub new_session_id: Option<String>,
     pub tenant_context: TenantContext,
+    /// Signed context for the gateway to verify locally on later requests
+    pub context_token: IssuedContextToken,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -227,7 +610,7 @@ pub struct TenantContext {
     pub tenant_name: String,
     pub tenant_slug: String,
     pub subscription_tier: SubscriptionTier,
-    pub features: Vec<String>,
+    pub feature_flags: FeatureFlagSet,
     pub quotas: TenantQuotas,
     pub settings: TenantSettings,
     pub user_role: TenantRole,
@@ -241,9 +624,21 @@ pub struct CreateTenantWorkflowRequest {
     pub admin_email: String,
     pub subscription_tier: SubscriptionTier,
     pub isolation_level: TenantIsolationLevel,
-    pub quotas: TenantQuotas,
+    /// Overrides the template quotas when set
+    #[serde(default)]
+    pub quotas: Option<TenantQuotas>,
+    /// Added on top of the template features
+    #[serde(default)]
     pub features: Vec<String>,
+    /// Installed in addition to the template modules
+    #[serde(default)]
     pub default_modules: Vec<String>,
+    /// Provisioning template; defaults to the template for the subscription tier
+    #[serde(default)]
+    pub template: Option<String>,
+    /// Starts a trial of this many days when set
+    #[serde(default)]
+    pub trial_days: Option<u32>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -251,6 +646,244 @@ pub struct CreateTenantWorkflowResult {
     pub tenant_id: TenantId,
     pub admin_user_id: UserId,
     pub database_connection: String,
+    pub template: String,
+    pub roles_created: Vec<String>,
+    pub modules_installed: Vec<String>,
+    pub sample_data_seeded: Vec<String>,
+    pub trial_workflow_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TenantTrialWorkflowRequest {
+    pub tenant_id: TenantId,
+    #[serde(default)]
+    pub trial_days: Option<u32>,
+    #[serde(default)]
+    pub grace_period_days: Option<u32>,
+    /// Days before expiry to send reminders; defaults to 7, 3 and 1
+    #[serde(default)]
+    pub reminder_days: Option<Vec<u32>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TenantTrialWorkflowResult {
+    pub tenant_id: TenantId,
+    pub workflow_id: String,
+    pub outcome: TrialPhase,
+    pub subscription_tier: SubscriptionTier,
+    pub expires_at: DateTime<Utc>,
+    pub reminders_sent: Vec<u32>,
+    pub extensions: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportTenantWorkflowRequest {
+    pub tenant_id: TenantId,
+    pub requested_by: Option<UserId>,
+    /// Directory the archive is written to; defaults to the service export directory
+    pub destination: Option<String>,
+    /// Environment name recorded in the archive manifest
+    pub source_environment: Option<String>,
+    #[serde(default = "default_true")]
+    pub include_users: bool,
+    #[serde(default = "default_true")]
+    pub include_files: bool,
+    #[serde(default = "default_true")]
+    pub include_workflow_history: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportTenantWorkflowResult {
+    pub tenant_id: TenantId,
+    pub archive_id: String,
+    pub archive_location: String,
+    pub format_version: u32,
+    pub schema_version: u32,
+    pub checksum: String,
+    pub size_bytes: u64,
+    pub users_exported: usize,
+    pub files_exported: usize,
+    pub workflow_references_exported: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportTenantWorkflowRequest {
+    pub archive_location: String,
+    /// Restore under a different name, e.g. when the source tenant still exists
+    pub target_tenant_name: Option<String>,
+    pub target_admin_email: Option<String>,
+    pub requested_by: Option<UserId>,
+    /// Validate the archive without restoring anything
+    #[serde(default)]
+    pub dry_run: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ImportTenantWorkflowResult {
+    pub tenant_id: Option<TenantId>,
+    pub archive_id: String,
+    pub source_tenant_id: TenantId,
+    pub source_environment: String,
+    pub dry_run: bool,
+    pub memberships_restored: usize,
+    pub files_restored: usize,
+    pub workflow_references_restored: usize,
+    pub warnings: Vec<String>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CloneTenantWorkflowRequest {
+    pub source_tenant_id: TenantId,
+    pub target_tenant_name: String,
+    /// Admin for the clone; defaults to the (anonymized) source admin email
+    pub target_admin_email: Option<String>,
+    pub requested_by: UserId,
+    /// Copy tenant memberships so the same team can use the clone
+    #[serde(default = "default_true")]
+    pub include_members: bool,
+    /// Copy file metadata into the clone
+    #[serde(default)]
+    pub include_data: bool,
+    /// Pseudonymize personal data in everything copied
+    #[serde(default = "default_true")]
+    pub anonymize: bool,
+    #[serde(default)]
+    pub additional_modules: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CloneTenantWorkflowResult {
+    pub tenant_id: TenantId,
+    pub source_tenant_id: TenantId,
+    pub database_connection: String,
+    pub roles_created: Vec<String>,
+    pub modules_installed: Vec<String>,
+    pub memberships_copied: usize,
+    pub files_copied: usize,
+    pub anonymized: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateSubTenantWorkflowRequest {
+    pub parent_tenant_id: TenantId,
+    pub tenant_name: String,
+    pub admin_email: String,
+    pub quotas: Option<TenantQuotas>,
+    #[serde(default)]
+    pub features: Vec<String>,
+    #[serde(default = "default_true")]
+    pub inherit_settings: bool,
+    pub requested_by: UserId,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateSubTenantWorkflowResult {
+    pub tenant_id: TenantId,
+    pub parent_tenant_id: TenantId,
+    pub quotas: TenantQuotas,
+    pub database_connection: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DetachSubTenantWorkflowRequest {
+    pub tenant_id: TenantId,
+    pub requested_by: UserId,
+    pub subscription_tier: Option<SubscriptionTier>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DetachSubTenantWorkflowResult {
+    pub tenant_id: TenantId,
+    pub former_parent_tenant_id: TenantId,
+    pub subscription_tier: SubscriptionTier,
+    pub settings_materialized: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchiveTenantWorkflowRequest {
+    pub tenant_id: TenantId,
+    pub requested_by: UserId,
+    pub reason: Option<String>,
+    /// Cold storage location; defaults to the service's cold storage directory
+    pub destination: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ArchiveTenantWorkflowResult {
+    pub tenant_id: TenantId,
+    pub archive_id: String,
+    pub location: String,
+    pub compressed_size_bytes: u64,
+    pub uncompressed_size_bytes: u64,
+    pub memberships_released: usize,
+    pub archived_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RestoreArchivedTenantWorkflowRequest {
+    pub tenant_id: TenantId,
+    pub requested_by: UserId,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct RestoreArchivedTenantWorkflowResult {
+    pub tenant_id: TenantId,
+    pub archive_id: String,
+    pub status: TenantStatus,
+    pub memberships_restored: usize,
+    pub sla_deadline: DateTime<Utc>,
+    pub completed_at: DateTime<Utc>,
+    pub sla_breached: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TerminateTenantWorkflowRequest {
+    pub tenant_id: TenantId,
+    pub requested_by: UserId,
+    pub reason: Option<String>,
+    /// Write a tenant archive before anything is purged
+    pub export_data: bool,
+    /// Days between the soft delete and the purge; defaults to 30
+    pub retention_days: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TerminateTenantWorkflowResult {
+    pub tenant_id: TenantId,
+    pub stage: TenantDeletionStage,
+    pub soft_deleted_at: DateTime<Utc>,
+    pub export_location: Option<String>,
+    pub services_purged: Vec<ServicePurgeResult>,
+    pub purged_at: Option<DateTime<Utc>>,
+    pub certificate_id: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MigrateTenantRegionWorkflowRequest {
+    pub tenant_id: TenantId,
+    pub target_region: String,
+    pub requested_by: UserId,
+    /// Data residency zone the tenant must stay in, e.g. "eu"
+    pub required_jurisdiction: Option<String>,
+    /// Longest the tenant may stay read-only before the migration is rolled back
+    pub max_freeze_minutes: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct MigrateTenantRegionWorkflowResult {
+    pub tenant_id: TenantId,
+    pub source_region: String,
+    pub target_region: String,
+    pub records_replicated: u64,
+    pub objects_copied: u64,
+    pub bytes_copied: u64,
+    pub domains_cut_over: Vec<String>,
+    pub freeze_duration_ms: u64,
+    pub completed_at: DateTime<Utc>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -267,6 +900,7 @@ pub struct SwitchTenantWorkflowResult {
     pub new_session_id: String,
     pub tenant_context: TenantContext,
     pub available_features: Vec<String>,
+    pub context_token: IssuedContextToken,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
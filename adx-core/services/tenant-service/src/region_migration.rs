@@ -0,0 +1,181 @@
+use serde::{Deserialize, Serialize};
+
+// Cross-region tenant migration. Data is bulk-copied to the target region while
+// the tenant stays writable, then writes are frozen for a short window while the
+// final delta is synced, verified and traffic is cut over. Each completed step
+// is recorded so a failure can be unwound in reverse order.
+
+/// Region a tenant lives in when it has never been migrated
+pub const DEFAULT_REGION: &str = "us-east-1";
+
+pub const DEFAULT_FREEZE_WINDOW_MINUTES: u32 = 30;
+pub const MAX_FREEZE_WINDOW_MINUTES: u32 = 240;
+
+/// Regions tenants can be placed in, with the jurisdiction each belongs to
+pub const SUPPORTED_REGIONS: &[(&str, &str)] = &[
+    ("us-east-1", "us"),
+    ("us-west-2", "us"),
+    ("eu-west-1", "eu"),
+    ("eu-central-1", "eu"),
+    ("ap-southeast-1", "apac"),
+];
+
+pub fn region_migration_workflow_id(tenant_id: &str) -> String {
+    format!("tenant-region-migration-{}", tenant_id)
+}
+
+pub fn region_jurisdiction(region: &str) -> Option<&'static str> {
+    SUPPORTED_REGIONS
+        .iter()
+        .find(|(name, _)| *name == region)
+        .map(|(_, jurisdiction)| *jurisdiction)
+}
+
+/// Check a move between regions. `required_jurisdiction` pins the tenant to a
+/// data-residency zone such as "eu".
+pub fn validate_region_move(
+    source_region: &str,
+    target_region: &str,
+    required_jurisdiction: Option<&str>,
+) -> Result<(), Vec<String>> {
+    let mut errors = Vec::new();
+
+    let target_jurisdiction = region_jurisdiction(target_region);
+    if target_jurisdiction.is_none() {
+        errors.push(format!("Target region '{}' is not supported", target_region));
+    }
+
+    if source_region == target_region {
+        errors.push(format!("Tenant is already in region '{}'", target_region));
+    }
+
+    if let (Some(required), Some(target)) = (required_jurisdiction, target_jurisdiction) {
+        if required != target {
+            errors.push(format!(
+                "Target region '{}' is outside the required '{}' data residency zone",
+                target_region, required
+            ));
+        }
+    }
+
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors)
+    }
+}
+
+pub fn validate_freeze_window(minutes: u32) -> Result<(), String> {
+    if minutes == 0 || minutes > MAX_FREEZE_WINDOW_MINUTES {
+        return Err(format!(
+            "Freeze window must be between 1 and {} minutes",
+            MAX_FREEZE_WINDOW_MINUTES
+        ));
+    }
+    Ok(())
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum RegionMigrationStep {
+    ReplicateDatabase,
+    CopyStorage,
+    FreezeWrites,
+    SyncDelta,
+    VerifyReplica,
+    CutoverDomains,
+    SwitchRegion,
+    UnfreezeWrites,
+}
+
+impl RegionMigrationStep {
+    /// Whether undoing this step needs an explicit action. Delta sync and
+    /// verification only touch the target copy, which replication cleanup drops.
+    pub fn needs_compensation(&self) -> bool {
+        matches!(
+            self,
+            Self::ReplicateDatabase | Self::CopyStorage | Self::FreezeWrites | Self::CutoverDomains | Self::SwitchRegion
+        )
+    }
+}
+
+/// Steps completed so far in one migration
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RegionMigrationProgress {
+    pub completed: Vec<RegionMigrationStep>,
+}
+
+impl RegionMigrationProgress {
+    pub fn record(&mut self, step: RegionMigrationStep) {
+        self.completed.push(step);
+    }
+
+    /// Steps to undo after a failure, most recent first. Once writes have been
+    /// unfrozen in the new region the migration is committed and nothing is undone.
+    pub fn compensations(&self) -> Vec<RegionMigrationStep> {
+        if self.completed.contains(&RegionMigrationStep::UnfreezeWrites) {
+            return Vec::new();
+        }
+
+        self.completed
+            .iter()
+            .rev()
+            .filter(|step| step.needs_compensation())
+            .copied()
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_region_move_validation() {
+        assert!(validate_region_move("us-east-1", "eu-west-1", None).is_ok());
+        assert!(validate_region_move("us-east-1", "eu-west-1", Some("eu")).is_ok());
+
+        let errors = validate_region_move("eu-west-1", "us-east-1", Some("eu")).unwrap_err();
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("data residency"));
+
+        let errors = validate_region_move("mars-1", "mars-1", None).unwrap_err();
+        assert_eq!(errors.len(), 2);
+    }
+
+    #[test]
+    fn test_compensations_unwind_in_reverse() {
+        let mut progress = RegionMigrationProgress::default();
+        for step in [
+            RegionMigrationStep::ReplicateDatabase,
+            RegionMigrationStep::CopyStorage,
+            RegionMigrationStep::FreezeWrites,
+            RegionMigrationStep::SyncDelta,
+            RegionMigrationStep::VerifyReplica,
+            RegionMigrationStep::CutoverDomains,
+        ] {
+            progress.record(step);
+        }
+
+        assert_eq!(
+            progress.compensations(),
+            vec![
+                RegionMigrationStep::CutoverDomains,
+                RegionMigrationStep::FreezeWrites,
+                RegionMigrationStep::CopyStorage,
+                RegionMigrationStep::ReplicateDatabase,
+            ]
+        );
+
+        progress.record(RegionMigrationStep::SwitchRegion);
+        progress.record(RegionMigrationStep::UnfreezeWrites);
+        assert!(progress.compensations().is_empty());
+    }
+
+    #[test]
+    fn test_freeze_window_bounds() {
+        assert!(validate_freeze_window(DEFAULT_FREEZE_WINDOW_MINUTES).is_ok());
+        assert!(validate_freeze_window(0).is_err());
+        assert!(validate_freeze_window(MAX_FREEZE_WINDOW_MINUTES + 1).is_err());
+    }
+}
@@ -1,33 +1,215 @@
 use async_trait::async_trait;
-use sqlx::PgPool;
-use chrono::Utc;
+use anyhow::{anyhow, Result};
+use chrono::{DateTime, Utc};
+use serde::de::DeserializeOwned;
+use sqlx::{postgres::PgRow, PgPool, Postgres, Row, Transaction};
 use uuid::Uuid;
-use anyhow::Result;
 
+use crate::feature_flags::ALL_FEATURES;
+use crate::lifecycle_events::{TenantLifecycleEvent, TenantLifecycleEventType};
 use crate::models::*;
-use adx_shared::types::{TenantId, UserId};
+use crate::repository_traits::{
+    FeatureFlagRepository, LifecycleEventRepository, TenantArchiveRepository, TenantConfigRepository, TenantDeletionRepository, TenantRepository, TenantMembershipRepository, TenantTemplateRepository,
+};
+use crate::templates::builtin_templates;
+use crate::tenant_config::builtin_namespaces;
+use adx_shared::database::isolation::TENANT_ID_SETTING;
+use adx_shared::types::{SubscriptionTier, TenantId, TenantIsolationLevel, UserId};
 
-#[async_trait]
-pub trait TenantRepository: Send + Sync {
-    async fn create(&self, tenant: &Tenant) -> Result<Tenant>;
-    async fn find_by_id(&self, id: &TenantId) -> Result<Option<Tenant>>;
-    async fn find_by_slug(&self, slug: &str) -> Result<Option<Tenant>>;
-    async fn find_by_name(&self, name: &str) -> Result<Option<Tenant>>;
-    async fn list(&self, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<Tenant>>;
-    async fn update(&self, tenant: &Tenant) -> Result<Tenant>;
-    async fn delete(&self, id: &TenantId) -> Result<()>;
-    async fn count(&self) -> Result<u64>;
+// Postgres-backed repositories. Queries are checked at runtime instead of
+// with the sqlx::query! macros, so the service builds without a database or
+// offline query metadata. Tables with row-level security are only touched
+// inside a transaction that sets the tenant context, which keeps the policies
+// in force even if a query loses its tenant filter. Admin reads that span
+// tenants set app.platform_access instead (migration 017).
+
+const PLATFORM_ACCESS_SETTING: &str = "app.platform_access";
+/// Once a pooled connection has set the tenant id, reading it outside a
+/// transaction gives '' rather than NULL, which the policies' UUID cast
+/// rejects. Platform reads set the nil id so every policy still evaluates.
+const NO_TENANT_ID: &str = "00000000-0000-0000-0000-000000000000";
+
+enum Scope<'a> {
+    Tenant(&'a TenantId),
+    Platform,
 }
 
-#[async_trait]
-pub trait TenantMembershipRepository: Send + Sync {
-    async fn create(&self, membership: &TenantMembership) -> Result<TenantMembership>;
-    async fn find_by_id(&self, id: &str) -> Result<Option<TenantMembership>>;
-    async fn find_by_tenant_and_user(&self, tenant_id: &TenantId, user_id: &UserId) -> Result<Option<TenantMembership>>;
-    async fn list_by_tenant(&self, tenant_id: &TenantId) -> Result<Vec<TenantMembership>>;
-    async fn list_by_user(&self, user_id: &UserId) -> Result<Vec<TenantMembership>>;
-    async fn update(&self, membership: &TenantMembership) -> Result<TenantMembership>;
-    async fn delete(&self, id: &str) -> Result<()>;
+async fn begin_scoped(pool: &PgPool, scope: Scope<'_>) -> Result<Transaction<'static, Postgres>> {
+    let mut tx = pool.begin().await?;
+
+    let (tenant_id, platform_access) = match scope {
+        Scope::Tenant(tenant_id) => (tenant_id.as_str(), "off"),
+        Scope::Platform => (NO_TENANT_ID, "on"),
+    };
+    sqlx::query("SELECT set_config($1, $2, true), set_config($3, $4, true)")
+        .bind(TENANT_ID_SETTING)
+        .bind(tenant_id)
+        .bind(PLATFORM_ACCESS_SETTING)
+        .bind(platform_access)
+        .execute(&mut *tx)
+        .await?;
+
+    Ok(tx)
+}
+
+/// Ids are strings in the models and UUIDs in the database. Lookups treat a
+/// malformed id as missing; writes reject it.
+fn parse_id(id: &str) -> Option<Uuid> {
+    Uuid::parse_str(id).ok()
+}
+
+fn require_id(id: &str, what: &str) -> Result<Uuid> {
+    parse_id(id).ok_or_else(|| anyhow!("Invalid {} id: {}", what, id))
+}
+
+fn optional_id(id: &Option<String>, what: &str) -> Result<Option<Uuid>> {
+    id.as_deref().map(|id| require_id(id, what)).transpose()
+}
+
+fn optional_string(id: Option<Uuid>) -> Option<String> {
+    id.map(|id| id.to_string())
+}
+
+/// Columns created by other services may hold '{}' or NULL
+fn json_or_default<T: DeserializeOwned + Default>(value: Option<serde_json::Value>) -> Result<T> {
+    match value {
+        None | Some(serde_json::Value::Null) => Ok(T::default()),
+        Some(serde_json::Value::Object(map)) if map.is_empty() => Ok(T::default()),
+        Some(value) => Ok(serde_json::from_value(value)?),
+    }
+}
+
+fn tier_to_db(tier: &SubscriptionTier) -> &'static str {
+    match tier {
+        SubscriptionTier::Free => "free",
+        SubscriptionTier::Professional => "professional",
+        SubscriptionTier::Enterprise => "enterprise",
+        SubscriptionTier::Custom => "custom",
+    }
+}
+
+fn tier_from_db(value: &str) -> Result<SubscriptionTier> {
+    match value {
+        "free" => Ok(SubscriptionTier::Free),
+        "professional" => Ok(SubscriptionTier::Professional),
+        "enterprise" => Ok(SubscriptionTier::Enterprise),
+        "custom" => Ok(SubscriptionTier::Custom),
+        other => Err(anyhow!("Unknown subscription tier '{}'", other)),
+    }
+}
+
+fn isolation_to_db(level: &TenantIsolationLevel) -> &'static str {
+    match level {
+        TenantIsolationLevel::Schema => "schema",
+        TenantIsolationLevel::Database => "database",
+        TenantIsolationLevel::Row => "row",
+    }
+}
+
+fn isolation_from_db(value: &str) -> Result<TenantIsolationLevel> {
+    match value {
+        "schema" => Ok(TenantIsolationLevel::Schema),
+        "database" => Ok(TenantIsolationLevel::Database),
+        "row" => Ok(TenantIsolationLevel::Row),
+        other => Err(anyhow!("Unknown isolation level '{}'", other)),
+    }
+}
+
+fn status_to_db(status: &TenantStatus) -> &'static str {
+    match status {
+        TenantStatus::Active => "active",
+        TenantStatus::Suspended => "suspended",
+        TenantStatus::Pending => "pending",
+        TenantStatus::Cancelled => "cancelled",
+        TenantStatus::ReadOnly => "read_only",
+        TenantStatus::Archived => "archived",
+        TenantStatus::PendingDeletion => "pending_deletion",
+    }
+}
+
+fn status_from_db(value: &str) -> Result<TenantStatus> {
+    match value {
+        "active" => Ok(TenantStatus::Active),
+        "suspended" => Ok(TenantStatus::Suspended),
+        "pending" => Ok(TenantStatus::Pending),
+        "cancelled" => Ok(TenantStatus::Cancelled),
+        "read_only" => Ok(TenantStatus::ReadOnly),
+        "archived" => Ok(TenantStatus::Archived),
+        "pending_deletion" => Ok(TenantStatus::PendingDeletion),
+        other => Err(anyhow!("Unknown tenant status '{}'", other)),
+    }
+}
+
+fn role_to_db(role: &TenantRole) -> &'static str {
+    match role {
+        TenantRole::Owner => "owner",
+        TenantRole::Admin => "admin",
+        TenantRole::Member => "member",
+        TenantRole::Guest => "guest",
+    }
+}
+
+fn role_from_db(value: &str) -> Result<TenantRole> {
+    match value {
+        "owner" => Ok(TenantRole::Owner),
+        "admin" => Ok(TenantRole::Admin),
+        "member" => Ok(TenantRole::Member),
+        "guest" => Ok(TenantRole::Guest),
+        other => Err(anyhow!("Unknown tenant role '{}'", other)),
+    }
+}
+
+fn membership_status_to_db(status: &MembershipStatus) -> &'static str {
+    match status {
+        MembershipStatus::Active => "active",
+        MembershipStatus::Invited => "invited",
+        MembershipStatus::Suspended => "suspended",
+        MembershipStatus::Removed => "removed",
+    }
+}
+
+fn membership_status_from_db(value: &str) -> Result<MembershipStatus> {
+    match value {
+        "active" => Ok(MembershipStatus::Active),
+        "invited" => Ok(MembershipStatus::Invited),
+        "suspended" => Ok(MembershipStatus::Suspended),
+        "removed" => Ok(MembershipStatus::Removed),
+        other => Err(anyhow!("Unknown membership status '{}'", other)),
+    }
+}
+
+fn event_type_from_db(value: &str) -> Result<TenantLifecycleEventType> {
+    serde_json::from_value(serde_json::Value::String(value.to_string()))
+        .map_err(|_| anyhow!("Unknown lifecycle event type '{}'", value))
+}
+
+/// Tenants that can still be read; other services only look at is_active
+fn is_serving(status: &TenantStatus) -> bool {
+    matches!(status, TenantStatus::Active | TenantStatus::ReadOnly)
+}
+
+const TENANT_COLUMNS: &str = "id, name, slug, admin_email, subscription_tier::text AS subscription_tier, \
+    isolation_level::text AS isolation_level, quotas, features, settings, status, parent_tenant_id, \
+    inherit_settings, region, created_at, updated_at";
+
+fn tenant_from_row(row: &PgRow) -> Result<Tenant> {
+    Ok(Tenant {
+        id: row.try_get::<Uuid, _>("id")?.to_string(),
+        name: row.try_get("name")?,
+        slug: row.try_get("slug")?,
+        admin_email: row.try_get("admin_email")?,
+        subscription_tier: tier_from_db(row.try_get("subscription_tier")?)?,
+        isolation_level: isolation_from_db(row.try_get("isolation_level")?)?,
+        quotas: json_or_default(row.try_get("quotas")?)?,
+        features: row.try_get::<Option<Vec<String>>, _>("features")?.unwrap_or_default(),
+        settings: json_or_default(row.try_get("settings")?)?,
+        status: status_from_db(row.try_get("status")?)?,
+        parent_tenant_id: optional_string(row.try_get("parent_tenant_id")?),
+        inherit_settings: row.try_get("inherit_settings")?,
+        region: row.try_get("region")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
 }
 
 pub struct PostgresTenantRepository {
@@ -49,6 +231,16 @@ impl PostgresTenantRepository {
             .collect::<Vec<_>>()
             .join("-")
     }
+
+    async fn find_one(&self, column: &str, value: &str) -> Result<Option<Tenant>> {
+        let sql = format!("SELECT {} FROM tenants WHERE {} = $1", TENANT_COLUMNS, column);
+        let row = sqlx::query(&sql)
+            .bind(value)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(tenant_from_row).transpose()
+    }
 }
 
 #[async_trait]
@@ -62,30 +254,33 @@ impl TenantRepository for PostgresTenantRepository {
             new_tenant.slug = Self::generate_slug(&new_tenant.name);
         }
         new_tenant.created_at = Utc::now();
-        new_tenant.updated_at = Utc::now();
-
-        let settings_json = serde_json::to_value(&new_tenant.settings)?;
-        let quotas_json = serde_json::to_value(&new_tenant.quotas)?;
+        new_tenant.updated_at = new_tenant.created_at;
 
         sqlx::query(
             r#"
             INSERT INTO tenants (
                 id, name, slug, admin_email, subscription_tier, isolation_level,
-                quotas, features, settings, status, created_at, updated_at
+                quotas, features, settings, is_active, status, parent_tenant_id,
+                inherit_settings, region, created_at, updated_at
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            VALUES ($1, $2, $3, $4, $5::subscription_tier, $6::tenant_isolation_level,
+                    $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
             "#
         )
-        .bind(&new_tenant.id)
+        .bind(require_id(&new_tenant.id, "tenant")?)
         .bind(&new_tenant.name)
         .bind(&new_tenant.slug)
         .bind(&new_tenant.admin_email)
-        .bind(serde_json::to_string(&new_tenant.subscription_tier)?)
-        .bind(serde_json::to_string(&new_tenant.isolation_level)?)
-        .bind(quotas_json)
+        .bind(tier_to_db(&new_tenant.subscription_tier))
+        .bind(isolation_to_db(&new_tenant.isolation_level))
+        .bind(serde_json::to_value(&new_tenant.quotas)?)
         .bind(&new_tenant.features)
-        .bind(settings_json)
-        .bind(serde_json::to_string(&new_tenant.status)?)
+        .bind(serde_json::to_value(&new_tenant.settings)?)
+        .bind(is_serving(&new_tenant.status))
+        .bind(status_to_db(&new_tenant.status))
+        .bind(optional_id(&new_tenant.parent_tenant_id, "parent tenant")?)
+        .bind(new_tenant.inherit_settings)
+        .bind(&new_tenant.region)
         .bind(new_tenant.created_at)
         .bind(new_tenant.updated_at)
         .execute(&self.pool)
@@ -95,188 +290,139 @@ impl TenantRepository for PostgresTenantRepository {
     }
 
     async fn find_by_id(&self, id: &TenantId) -> Result<Option<Tenant>> {
-        let row = sqlx::query!(
-            "SELECT * FROM tenants WHERE id = $1",
-            id
-        )
-        .fetch_optional(&self.pool)
-        .await?;
+        let Some(id) = parse_id(id) else {
+            return Ok(None);
+        };
 
-        if let Some(row) = row {
-            let tenant = Tenant {
-                id: row.id,
-                name: row.name,
-                slug: row.slug,
-                admin_email: row.admin_email,
-                subscription_tier: serde_json::from_str(&row.subscription_tier)?,
-                isolation_level: serde_json::from_str(&row.isolation_level)?,
-                quotas: serde_json::from_value(row.quotas)?,
-                features: row.features,
-                settings: serde_json::from_value(row.settings)?,
-                status: serde_json::from_str(&row.status)?,
-                created_at: row.created_at,
-                updated_at: row.updated_at,
-            };
-            Ok(Some(tenant))
-        } else {
-            Ok(None)
-        }
+        let sql = format!("SELECT {} FROM tenants WHERE id = $1", TENANT_COLUMNS);
+        let row = sqlx::query(&sql)
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(tenant_from_row).transpose()
     }
 
     async fn find_by_slug(&self, slug: &str) -> Result<Option<Tenant>> {
-        let row = sqlx::query!(
-            "SELECT * FROM tenants WHERE slug = $1",
-            slug
-        )
-        .fetch_optional(&self.pool)
-        .await?;
-
-        if let Some(row) = row {
-            let tenant = Tenant {
-                id: row.id,
-                name: row.name,
-                slug: row.slug,
-                admin_email: row.admin_email,
-                subscription_tier: serde_json::from_str(&row.subscription_tier)?,
-                isolation_level: serde_json::from_str(&row.isolation_level)?,
-                quotas: serde_json::from_value(row.quotas)?,
-                features: row.features,
-                settings: serde_json::from_value(row.settings)?,
-                status: serde_json::from_str(&row.status)?,
-                created_at: row.created_at,
-                updated_at: row.updated_at,
-            };
-            Ok(Some(tenant))
-        } else {
-            Ok(None)
-        }
+        self.find_one("slug", slug).await
     }
 
     async fn find_by_name(&self, name: &str) -> Result<Option<Tenant>> {
-        let row = sqlx::query!(
-            "SELECT * FROM tenants WHERE name = $1",
-            name
-        )
-        .fetch_optional(&self.pool)
-        .await?;
-
-        if let Some(row) = row {
-            let tenant = Tenant {
-                id: row.id,
-                name: row.name,
-                slug: row.slug,
-                admin_email: row.admin_email,
-                subscription_tier: serde_json::from_str(&row.subscription_tier)?,
-                isolation_level: serde_json::from_str(&row.isolation_level)?,
-                quotas: serde_json::from_value(row.quotas)?,
-                features: row.features,
-                settings: serde_json::from_value(row.settings)?,
-                status: serde_json::from_str(&row.status)?,
-                created_at: row.created_at,
-                updated_at: row.updated_at,
-            };
-            Ok(Some(tenant))
-        } else {
-            Ok(None)
-        }
+        self.find_one("name", name).await
     }
 
     async fn list(&self, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<Tenant>> {
-        let limit = limit.unwrap_or(50) as i64;
-        let offset = offset.unwrap_or(0) as i64;
+        let sql = format!("SELECT {} FROM tenants ORDER BY created_at DESC LIMIT $1 OFFSET $2", TENANT_COLUMNS);
+        let rows = sqlx::query(&sql)
+            .bind(limit.unwrap_or(50) as i64)
+            .bind(offset.unwrap_or(0) as i64)
+            .fetch_all(&self.pool)
+            .await?;
 
-        let rows = sqlx::query!(
-            "SELECT * FROM tenants ORDER BY created_at DESC LIMIT $1 OFFSET $2",
-            limit,
-            offset
-        )
-        .fetch_all(&self.pool)
-        .await?;
+        rows.iter().map(tenant_from_row).collect()
+    }
 
-        let mut tenants = Vec::new();
-        for row in rows {
-            let tenant = Tenant {
-                id: row.id,
-                name: row.name,
-                slug: row.slug,
-                admin_email: row.admin_email,
-                subscription_tier: serde_json::from_str(&row.subscription_tier)?,
-                isolation_level: serde_json::from_str(&row.isolation_level)?,
-                quotas: serde_json::from_value(row.quotas)?,
-                features: row.features,
-                settings: serde_json::from_value(row.settings)?,
-                status: serde_json::from_str(&row.status)?,
-                created_at: row.created_at,
-                updated_at: row.updated_at,
-            };
-            tenants.push(tenant);
-        }
+    async fn list_children(&self, parent_id: &TenantId) -> Result<Vec<Tenant>> {
+        let Some(parent_id) = parse_id(parent_id) else {
+            return Ok(vec![]);
+        };
+
+        let sql = format!("SELECT {} FROM tenants WHERE parent_tenant_id = $1 ORDER BY created_at", TENANT_COLUMNS);
+        let rows = sqlx::query(&sql)
+            .bind(parent_id)
+            .fetch_all(&self.pool)
+            .await?;
 
-        Ok(tenants)
+        rows.iter().map(tenant_from_row).collect()
     }
 
     async fn update(&self, tenant: &Tenant) -> Result<Tenant> {
         let mut updated_tenant = tenant.clone();
         updated_tenant.updated_at = Utc::now();
 
-        let settings_json = serde_json::to_value(&updated_tenant.settings)?;
-        let quotas_json = serde_json::to_value(&updated_tenant.quotas)?;
-
-        sqlx::query!(
+        let result = sqlx::query(
             r#"
             UPDATE tenants SET
                 name = $2,
                 slug = $3,
                 admin_email = $4,
-                subscription_tier = $5,
-                isolation_level = $6,
+                subscription_tier = $5::subscription_tier,
+                isolation_level = $6::tenant_isolation_level,
                 quotas = $7,
                 features = $8,
                 settings = $9,
-                status = $10,
-                updated_at = $11
+                is_active = $10,
+                status = $11,
+                parent_tenant_id = $12,
+                inherit_settings = $13,
+                region = $14,
+                updated_at = $15
             WHERE id = $1
-            "#,
-            updated_tenant.id,
-            updated_tenant.name,
-            updated_tenant.slug,
-            updated_tenant.admin_email,
-            serde_json::to_string(&updated_tenant.subscription_tier)?,
-            serde_json::to_string(&updated_tenant.isolation_level)?,
-            quotas_json,
-            &updated_tenant.features,
-            settings_json,
-            serde_json::to_string(&updated_tenant.status)?,
-            updated_tenant.updated_at
+            "#
         )
+        .bind(require_id(&updated_tenant.id, "tenant")?)
+        .bind(&updated_tenant.name)
+        .bind(&updated_tenant.slug)
+        .bind(&updated_tenant.admin_email)
+        .bind(tier_to_db(&updated_tenant.subscription_tier))
+        .bind(isolation_to_db(&updated_tenant.isolation_level))
+        .bind(serde_json::to_value(&updated_tenant.quotas)?)
+        .bind(&updated_tenant.features)
+        .bind(serde_json::to_value(&updated_tenant.settings)?)
+        .bind(is_serving(&updated_tenant.status))
+        .bind(status_to_db(&updated_tenant.status))
+        .bind(optional_id(&updated_tenant.parent_tenant_id, "parent tenant")?)
+        .bind(updated_tenant.inherit_settings)
+        .bind(&updated_tenant.region)
+        .bind(updated_tenant.updated_at)
         .execute(&self.pool)
         .await?;
 
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("Tenant not found"));
+        }
+
         Ok(updated_tenant)
     }
 
     async fn delete(&self, id: &TenantId) -> Result<()> {
-        sqlx::query!(
-            "DELETE FROM tenants WHERE id = $1",
-            id
-        )
-        .execute(&self.pool)
-        .await?;
-
+        if let Some(id) = parse_id(id) {
+            sqlx::query("DELETE FROM tenants WHERE id = $1")
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+        }
         Ok(())
     }
 
     async fn count(&self) -> Result<u64> {
-        let row = sqlx::query!(
-            "SELECT COUNT(*) as count FROM tenants"
-        )
-        .fetch_one(&self.pool)
-        .await?;
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM tenants")
+            .fetch_one(&self.pool)
+            .await?;
 
-        Ok(row.count.unwrap_or(0) as u64)
+        Ok(count as u64)
     }
 }
 
+const MEMBERSHIP_COLUMNS: &str = "id, tenant_id, user_id, role, permissions, status, invited_by, invited_at, \
+    joined_at, created_at, updated_at";
+
+fn membership_from_row(row: &PgRow) -> Result<TenantMembership> {
+    Ok(TenantMembership {
+        id: row.try_get::<Uuid, _>("id")?.to_string(),
+        tenant_id: row.try_get::<Uuid, _>("tenant_id")?.to_string(),
+        user_id: row.try_get::<Uuid, _>("user_id")?.to_string(),
+        role: role_from_db(row.try_get("role")?)?,
+        permissions: row.try_get::<Option<Vec<String>>, _>("permissions")?.unwrap_or_default(),
+        status: membership_status_from_db(row.try_get("status")?)?,
+        invited_by: optional_string(row.try_get("invited_by")?),
+        invited_at: row.try_get("invited_at")?,
+        joined_at: row.try_get("joined_at")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
 pub struct PostgresTenantMembershipRepository {
     pool: PgPool,
 }
@@ -295,184 +441,1193 @@ impl TenantMembershipRepository for PostgresTenantMembershipRepository {
             new_membership.id = Uuid::new_v4().to_string();
         }
         new_membership.created_at = Utc::now();
-        new_membership.updated_at = Utc::now();
+        new_membership.updated_at = new_membership.created_at;
 
-        sqlx::query!(
+        let mut tx = begin_scoped(&self.pool, Scope::Tenant(&new_membership.tenant_id)).await?;
+        sqlx::query(
             r#"
             INSERT INTO tenant_memberships (
-                id, tenant_id, user_id, role, permissions, status,
+                id, tenant_id, user_id, role, roles, permissions, status, is_active,
                 invited_by, invited_at, joined_at, created_at, updated_at
             )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
-            "#,
-            new_membership.id,
-            new_membership.tenant_id,
-            new_membership.user_id,
-            serde_json::to_string(&new_membership.role)?,
-            &new_membership.permissions,
-            serde_json::to_string(&new_membership.status)?,
-            new_membership.invited_by,
-            new_membership.invited_at,
-            new_membership.joined_at,
-            new_membership.created_at,
-            new_membership.updated_at
+            VALUES ($1, $2, $3, $4, ARRAY[$4], $5, $6, $7, $8, $9, $10, $11, $12)
+            "#
         )
-        .execute(&self.pool)
+        .bind(require_id(&new_membership.id, "membership")?)
+        .bind(require_id(&new_membership.tenant_id, "tenant")?)
+        .bind(require_id(&new_membership.user_id, "user")?)
+        .bind(role_to_db(&new_membership.role))
+        .bind(&new_membership.permissions)
+        .bind(membership_status_to_db(&new_membership.status))
+        .bind(new_membership.status == MembershipStatus::Active)
+        .bind(optional_id(&new_membership.invited_by, "user")?)
+        .bind(new_membership.invited_at)
+        .bind(new_membership.joined_at)
+        .bind(new_membership.created_at)
+        .bind(new_membership.updated_at)
+        .execute(&mut *tx)
         .await?;
+        tx.commit().await?;
 
         Ok(new_membership)
     }
 
     async fn find_by_id(&self, id: &str) -> Result<Option<TenantMembership>> {
-        let row = sqlx::query!(
-            "SELECT * FROM tenant_memberships WHERE id = $1",
-            id
+        let Some(id) = parse_id(id) else {
+            return Ok(None);
+        };
+
+        let mut tx = begin_scoped(&self.pool, Scope::Platform).await?;
+        let sql = format!("SELECT {} FROM tenant_memberships WHERE id = $1", MEMBERSHIP_COLUMNS);
+        let row = sqlx::query(&sql)
+            .bind(id)
+            .fetch_optional(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        row.as_ref().map(membership_from_row).transpose()
+    }
+
+    async fn find_by_tenant_and_user(&self, tenant_id: &TenantId, user_id: &UserId) -> Result<Option<TenantMembership>> {
+        let (Some(tenant_uuid), Some(user_uuid)) = (parse_id(tenant_id), parse_id(user_id)) else {
+            return Ok(None);
+        };
+
+        let mut tx = begin_scoped(&self.pool, Scope::Tenant(tenant_id)).await?;
+        let sql = format!("SELECT {} FROM tenant_memberships WHERE tenant_id = $1 AND user_id = $2", MEMBERSHIP_COLUMNS);
+        let row = sqlx::query(&sql)
+            .bind(tenant_uuid)
+            .bind(user_uuid)
+            .fetch_optional(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        row.as_ref().map(membership_from_row).transpose()
+    }
+
+    async fn list_by_tenant(&self, tenant_id: &TenantId) -> Result<Vec<TenantMembership>> {
+        let Some(tenant_uuid) = parse_id(tenant_id) else {
+            return Ok(vec![]);
+        };
+
+        let mut tx = begin_scoped(&self.pool, Scope::Tenant(tenant_id)).await?;
+        let sql = format!("SELECT {} FROM tenant_memberships WHERE tenant_id = $1 ORDER BY created_at DESC", MEMBERSHIP_COLUMNS);
+        let rows = sqlx::query(&sql)
+            .bind(tenant_uuid)
+            .fetch_all(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        rows.iter().map(membership_from_row).collect()
+    }
+
+    async fn list_by_user(&self, user_id: &UserId) -> Result<Vec<TenantMembership>> {
+        let Some(user_uuid) = parse_id(user_id) else {
+            return Ok(vec![]);
+        };
+
+        let mut tx = begin_scoped(&self.pool, Scope::Platform).await?;
+        let sql = format!("SELECT {} FROM tenant_memberships WHERE user_id = $1 ORDER BY created_at DESC", MEMBERSHIP_COLUMNS);
+        let rows = sqlx::query(&sql)
+            .bind(user_uuid)
+            .fetch_all(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        rows.iter().map(membership_from_row).collect()
+    }
+
+    async fn update(&self, membership: &TenantMembership) -> Result<TenantMembership> {
+        let mut updated_membership = membership.clone();
+        updated_membership.updated_at = Utc::now();
+
+        let mut tx = begin_scoped(&self.pool, Scope::Tenant(&updated_membership.tenant_id)).await?;
+        let result = sqlx::query(
+            r#"
+            UPDATE tenant_memberships SET
+                role = $3,
+                roles = ARRAY[$3],
+                permissions = $4,
+                status = $5,
+                is_active = $6,
+                joined_at = $7,
+                updated_at = $8
+            WHERE id = $1 AND tenant_id = $2
+            "#
         )
-        .fetch_optional(&self.pool)
+        .bind(require_id(&updated_membership.id, "membership")?)
+        .bind(require_id(&updated_membership.tenant_id, "tenant")?)
+        .bind(role_to_db(&updated_membership.role))
+        .bind(&updated_membership.permissions)
+        .bind(membership_status_to_db(&updated_membership.status))
+        .bind(updated_membership.status == MembershipStatus::Active)
+        .bind(updated_membership.joined_at)
+        .bind(updated_membership.updated_at)
+        .execute(&mut *tx)
         .await?;
+        tx.commit().await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("Membership not found"));
+        }
 
-        if let Some(row) = row {
-            let membership = TenantMembership {
-                id: row.id,
-                tenant_id: row.tenant_id,
-                user_id: row.user_id,
-                role: serde_json::from_str(&row.role)?,
-                permissions: row.permissions,
-                status: serde_json::from_str(&row.status)?,
-                invited_by: row.invited_by,
-                invited_at: row.invited_at,
-                joined_at: row.joined_at,
-                created_at: row.created_at,
-                updated_at: row.updated_at,
-            };
-            Ok(Some(membership))
-        } else {
-            Ok(None)
+        Ok(updated_membership)
+    }
+
+    async fn delete(&self, id: &str) -> Result<()> {
+        if let Some(id) = parse_id(id) {
+            let mut tx = begin_scoped(&self.pool, Scope::Platform).await?;
+            sqlx::query("DELETE FROM tenant_memberships WHERE id = $1")
+                .bind(id)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
         }
+        Ok(())
     }
+}
 
-    async fn find_by_tenant_and_user(&self, tenant_id: &TenantId, user_id: &UserId) -> Result<Option<TenantMembership>> {
-        let row = sqlx::query!(
-            "SELECT * FROM tenant_memberships WHERE tenant_id = $1 AND user_id = $2",
-            tenant_id,
-            user_id
+fn template_from_row(row: &PgRow) -> Result<TenantTemplate> {
+    let settings: Option<serde_json::Value> = row.try_get("settings")?;
+
+    Ok(TenantTemplate {
+        name: row.try_get("name")?,
+        display_name: row.try_get("display_name")?,
+        description: row.try_get("description")?,
+        subscription_tier: tier_from_db(row.try_get("subscription_tier")?)?,
+        quotas: json_or_default(row.try_get("quotas")?)?,
+        features: row.try_get("features")?,
+        default_roles: serde_json::from_value(row.try_get("default_roles")?)?,
+        enabled_modules: row.try_get("enabled_modules")?,
+        sample_data: row.try_get("sample_data")?,
+        settings: settings.map(serde_json::from_value).transpose()?,
+        is_builtin: row.try_get("is_builtin")?,
+        is_active: row.try_get("is_active")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+pub struct PostgresTenantTemplateRepository {
+    pool: PgPool,
+}
+
+impl PostgresTenantTemplateRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl TenantTemplateRepository for PostgresTenantTemplateRepository {
+    async fn create(&self, template: &TenantTemplate) -> Result<TenantTemplate> {
+        let mut new_template = template.clone();
+        new_template.created_at = Utc::now();
+        new_template.updated_at = new_template.created_at;
+
+        sqlx::query(
+            r#"
+            INSERT INTO tenant_templates (
+                name, display_name, description, subscription_tier, quotas, features,
+                default_roles, enabled_modules, sample_data, settings, is_builtin,
+                is_active, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14)
+            "#
         )
-        .fetch_optional(&self.pool)
+        .bind(&new_template.name)
+        .bind(&new_template.display_name)
+        .bind(&new_template.description)
+        .bind(tier_to_db(&new_template.subscription_tier))
+        .bind(serde_json::to_value(&new_template.quotas)?)
+        .bind(&new_template.features)
+        .bind(serde_json::to_value(&new_template.default_roles)?)
+        .bind(&new_template.enabled_modules)
+        .bind(&new_template.sample_data)
+        .bind(new_template.settings.as_ref().map(serde_json::to_value).transpose()?)
+        .bind(new_template.is_builtin)
+        .bind(new_template.is_active)
+        .bind(new_template.created_at)
+        .bind(new_template.updated_at)
+        .execute(&self.pool)
         .await?;
 
-        if let Some(row) = row {
-            let membership = TenantMembership {
-                id: row.id,
-                tenant_id: row.tenant_id,
-                user_id: row.user_id,
-                role: serde_json::from_str(&row.role)?,
-                permissions: row.permissions,
-                status: serde_json::from_str(&row.status)?,
-                invited_by: row.invited_by,
-                invited_at: row.invited_at,
-                joined_at: row.joined_at,
-                created_at: row.created_at,
-                updated_at: row.updated_at,
-            };
-            Ok(Some(membership))
-        } else {
-            Ok(None)
+        Ok(new_template)
+    }
+
+    async fn find_by_name(&self, name: &str) -> Result<Option<TenantTemplate>> {
+        let row = sqlx::query("SELECT * FROM tenant_templates WHERE name = $1")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(template_from_row).transpose()
+    }
+
+    async fn list(&self, include_inactive: bool) -> Result<Vec<TenantTemplate>> {
+        let rows = sqlx::query("SELECT * FROM tenant_templates WHERE $1 OR is_active ORDER BY name")
+            .bind(include_inactive)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(template_from_row).collect()
+    }
+
+    async fn update(&self, template: &TenantTemplate) -> Result<TenantTemplate> {
+        let mut updated_template = template.clone();
+        updated_template.updated_at = Utc::now();
+
+        let result = sqlx::query(
+            r#"
+            UPDATE tenant_templates SET
+                display_name = $2,
+                description = $3,
+                subscription_tier = $4,
+                quotas = $5,
+                features = $6,
+                default_roles = $7,
+                enabled_modules = $8,
+                sample_data = $9,
+                settings = $10,
+                is_active = $11,
+                updated_at = $12
+            WHERE name = $1
+            "#
+        )
+        .bind(&updated_template.name)
+        .bind(&updated_template.display_name)
+        .bind(&updated_template.description)
+        .bind(tier_to_db(&updated_template.subscription_tier))
+        .bind(serde_json::to_value(&updated_template.quotas)?)
+        .bind(&updated_template.features)
+        .bind(serde_json::to_value(&updated_template.default_roles)?)
+        .bind(&updated_template.enabled_modules)
+        .bind(&updated_template.sample_data)
+        .bind(updated_template.settings.as_ref().map(serde_json::to_value).transpose()?)
+        .bind(updated_template.is_active)
+        .bind(updated_template.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("Template not found"));
         }
+
+        Ok(updated_template)
     }
 
-    async fn list_by_tenant(&self, tenant_id: &TenantId) -> Result<Vec<TenantMembership>> {
-        let rows = sqlx::query!(
-            "SELECT * FROM tenant_memberships WHERE tenant_id = $1 ORDER BY created_at DESC",
-            tenant_id
+    async fn delete(&self, name: &str) -> Result<()> {
+        sqlx::query("DELETE FROM tenant_templates WHERE name = $1")
+            .bind(name)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+}
+
+fn flag_from_row(row: &PgRow) -> Result<FeatureFlag> {
+    let enabled_tiers: Vec<String> = row.try_get("enabled_tiers")?;
+
+    Ok(FeatureFlag {
+        key: row.try_get("key")?,
+        description: row.try_get("description")?,
+        default_enabled: row.try_get("default_enabled")?,
+        enabled_tiers: enabled_tiers.iter().map(|tier| tier_from_db(tier)).collect::<Result<_>>()?,
+        rollout_percentage: row.try_get::<i16, _>("rollout_percentage")? as u8,
+        is_active: row.try_get("is_active")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+fn flag_override_from_row(row: &PgRow) -> Result<TenantFlagOverride> {
+    Ok(TenantFlagOverride {
+        tenant_id: row.try_get::<Uuid, _>("tenant_id")?.to_string(),
+        flag_key: row.try_get("flag_key")?,
+        enabled: row.try_get("enabled")?,
+        reason: row.try_get("reason")?,
+        updated_by: optional_string(row.try_get("updated_by")?),
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+pub struct PostgresFeatureFlagRepository {
+    pool: PgPool,
+}
+
+impl PostgresFeatureFlagRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl FeatureFlagRepository for PostgresFeatureFlagRepository {
+    async fn create_flag(&self, flag: &FeatureFlag) -> Result<FeatureFlag> {
+        let mut new_flag = flag.clone();
+        new_flag.created_at = Utc::now();
+        new_flag.updated_at = new_flag.created_at;
+
+        let enabled_tiers: Vec<&str> = new_flag.enabled_tiers.iter().map(tier_to_db).collect();
+        sqlx::query(
+            r#"
+            INSERT INTO feature_flags (
+                key, description, default_enabled, enabled_tiers, rollout_percentage,
+                is_active, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#
         )
-        .fetch_all(&self.pool)
+        .bind(&new_flag.key)
+        .bind(&new_flag.description)
+        .bind(new_flag.default_enabled)
+        .bind(&enabled_tiers)
+        .bind(new_flag.rollout_percentage as i16)
+        .bind(new_flag.is_active)
+        .bind(new_flag.created_at)
+        .bind(new_flag.updated_at)
+        .execute(&self.pool)
         .await?;
 
-        let mut memberships = Vec::new();
-        for row in rows {
-            let membership = TenantMembership {
-                id: row.id,
-                tenant_id: row.tenant_id,
-                user_id: row.user_id,
-                role: serde_json::from_str(&row.role)?,
-                permissions: row.permissions,
-                status: serde_json::from_str(&row.status)?,
-                invited_by: row.invited_by,
-                invited_at: row.invited_at,
-                joined_at: row.joined_at,
-                created_at: row.created_at,
-                updated_at: row.updated_at,
-            };
-            memberships.push(membership);
+        Ok(new_flag)
+    }
+
+    async fn find_flag(&self, key: &str) -> Result<Option<FeatureFlag>> {
+        let row = sqlx::query("SELECT * FROM feature_flags WHERE key = $1")
+            .bind(key)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(flag_from_row).transpose()
+    }
+
+    async fn list_flags(&self, include_inactive: bool) -> Result<Vec<FeatureFlag>> {
+        let rows = sqlx::query("SELECT * FROM feature_flags WHERE $1 OR is_active ORDER BY key")
+            .bind(include_inactive)
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(flag_from_row).collect()
+    }
+
+    async fn update_flag(&self, flag: &FeatureFlag) -> Result<FeatureFlag> {
+        let mut updated_flag = flag.clone();
+        updated_flag.updated_at = Utc::now();
+
+        let enabled_tiers: Vec<&str> = updated_flag.enabled_tiers.iter().map(tier_to_db).collect();
+        let result = sqlx::query(
+            r#"
+            UPDATE feature_flags SET
+                description = $2,
+                default_enabled = $3,
+                enabled_tiers = $4,
+                rollout_percentage = $5,
+                is_active = $6,
+                updated_at = $7
+            WHERE key = $1
+            "#
+        )
+        .bind(&updated_flag.key)
+        .bind(&updated_flag.description)
+        .bind(updated_flag.default_enabled)
+        .bind(&enabled_tiers)
+        .bind(updated_flag.rollout_percentage as i16)
+        .bind(updated_flag.is_active)
+        .bind(updated_flag.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("Feature flag not found"));
         }
 
-        Ok(memberships)
+        Ok(updated_flag)
     }
 
-    async fn list_by_user(&self, user_id: &UserId) -> Result<Vec<TenantMembership>> {
-        let rows = sqlx::query!(
-            "SELECT * FROM tenant_memberships WHERE user_id = $1 ORDER BY created_at DESC",
-            user_id
+    async fn delete_flag(&self, key: &str) -> Result<()> {
+        // Overrides go with the flag through ON DELETE CASCADE
+        sqlx::query("DELETE FROM feature_flags WHERE key = $1")
+            .bind(key)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn set_override(&self, tenant_override: &TenantFlagOverride) -> Result<TenantFlagOverride> {
+        let mut new_override = tenant_override.clone();
+        new_override.updated_at = Utc::now();
+
+        let mut tx = begin_scoped(&self.pool, Scope::Tenant(&new_override.tenant_id)).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO tenant_feature_flag_overrides (tenant_id, flag_key, enabled, reason, updated_by, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (tenant_id, flag_key) DO UPDATE SET
+                enabled = EXCLUDED.enabled,
+                reason = EXCLUDED.reason,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = EXCLUDED.updated_at
+            "#
+        )
+        .bind(require_id(&new_override.tenant_id, "tenant")?)
+        .bind(&new_override.flag_key)
+        .bind(new_override.enabled)
+        .bind(&new_override.reason)
+        .bind(optional_id(&new_override.updated_by, "user")?)
+        .bind(new_override.updated_at)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(new_override)
+    }
+
+    async fn list_overrides(&self, tenant_id: &TenantId) -> Result<Vec<TenantFlagOverride>> {
+        let Some(tenant_uuid) = parse_id(tenant_id) else {
+            return Ok(vec![]);
+        };
+
+        let mut tx = begin_scoped(&self.pool, Scope::Tenant(tenant_id)).await?;
+        let rows = sqlx::query("SELECT * FROM tenant_feature_flag_overrides WHERE tenant_id = $1 ORDER BY flag_key")
+            .bind(tenant_uuid)
+            .fetch_all(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        rows.iter().map(flag_override_from_row).collect()
+    }
+
+    async fn delete_override(&self, tenant_id: &TenantId, key: &str) -> Result<()> {
+        if let Some(tenant_uuid) = parse_id(tenant_id) {
+            let mut tx = begin_scoped(&self.pool, Scope::Tenant(tenant_id)).await?;
+            sqlx::query("DELETE FROM tenant_feature_flag_overrides WHERE tenant_id = $1 AND flag_key = $2")
+                .bind(tenant_uuid)
+                .bind(key)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+        }
+        Ok(())
+    }
+}
+
+fn namespace_from_row(row: &PgRow) -> Result<ConfigNamespace> {
+    Ok(ConfigNamespace {
+        name: row.try_get("name")?,
+        description: row.try_get("description")?,
+        owner_service: row.try_get("owner_service")?,
+        schema: row.try_get("schema")?,
+        default_value: row.try_get("default_value")?,
+        version: row.try_get::<i32, _>("version")? as u32,
+        is_builtin: row.try_get("is_builtin")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+fn config_value_from_row(row: &PgRow) -> Result<TenantConfigValue> {
+    Ok(TenantConfigValue {
+        tenant_id: row.try_get::<Uuid, _>("tenant_id")?.to_string(),
+        namespace: row.try_get("namespace")?,
+        value: row.try_get("value")?,
+        version: row.try_get::<i64, _>("version")? as u64,
+        updated_by: optional_string(row.try_get("updated_by")?),
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+pub struct PostgresTenantConfigRepository {
+    pool: PgPool,
+}
+
+impl PostgresTenantConfigRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl TenantConfigRepository for PostgresTenantConfigRepository {
+    async fn create_namespace(&self, namespace: &ConfigNamespace) -> Result<ConfigNamespace> {
+        let mut new_namespace = namespace.clone();
+        new_namespace.created_at = Utc::now();
+        new_namespace.updated_at = new_namespace.created_at;
+
+        sqlx::query(
+            r#"
+            INSERT INTO config_namespaces (
+                name, description, owner_service, schema, default_value, version,
+                is_builtin, created_at, updated_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#
+        )
+        .bind(&new_namespace.name)
+        .bind(&new_namespace.description)
+        .bind(&new_namespace.owner_service)
+        .bind(&new_namespace.schema)
+        .bind(&new_namespace.default_value)
+        .bind(new_namespace.version as i32)
+        .bind(new_namespace.is_builtin)
+        .bind(new_namespace.created_at)
+        .bind(new_namespace.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(new_namespace)
+    }
+
+    async fn find_namespace(&self, name: &str) -> Result<Option<ConfigNamespace>> {
+        let row = sqlx::query("SELECT * FROM config_namespaces WHERE name = $1")
+            .bind(name)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(namespace_from_row).transpose()
+    }
+
+    async fn list_namespaces(&self) -> Result<Vec<ConfigNamespace>> {
+        let rows = sqlx::query("SELECT * FROM config_namespaces ORDER BY name")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(namespace_from_row).collect()
+    }
+
+    async fn update_namespace(&self, namespace: &ConfigNamespace) -> Result<ConfigNamespace> {
+        let mut updated_namespace = namespace.clone();
+        updated_namespace.updated_at = Utc::now();
+
+        let result = sqlx::query(
+            r#"
+            UPDATE config_namespaces SET
+                description = $2,
+                owner_service = $3,
+                schema = $4,
+                default_value = $5,
+                version = $6,
+                updated_at = $7
+            WHERE name = $1
+            "#
+        )
+        .bind(&updated_namespace.name)
+        .bind(&updated_namespace.description)
+        .bind(&updated_namespace.owner_service)
+        .bind(&updated_namespace.schema)
+        .bind(&updated_namespace.default_value)
+        .bind(updated_namespace.version as i32)
+        .bind(updated_namespace.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("Config namespace not found"));
+        }
+
+        Ok(updated_namespace)
+    }
+
+    async fn delete_namespace(&self, name: &str) -> Result<()> {
+        // Tenant values go with the namespace through ON DELETE CASCADE
+        sqlx::query("DELETE FROM config_namespaces WHERE name = $1")
+            .bind(name)
+            .execute(&self.pool)
+            .await?;
+        Ok(())
+    }
+
+    async fn find_value(&self, tenant_id: &TenantId, namespace: &str) -> Result<Option<TenantConfigValue>> {
+        let Some(tenant_uuid) = parse_id(tenant_id) else {
+            return Ok(None);
+        };
+
+        let mut tx = begin_scoped(&self.pool, Scope::Tenant(tenant_id)).await?;
+        let row = sqlx::query("SELECT * FROM tenant_config_values WHERE tenant_id = $1 AND namespace = $2")
+            .bind(tenant_uuid)
+            .bind(namespace)
+            .fetch_optional(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        row.as_ref().map(config_value_from_row).transpose()
+    }
+
+    async fn list_values(&self, tenant_id: &TenantId) -> Result<Vec<TenantConfigValue>> {
+        let Some(tenant_uuid) = parse_id(tenant_id) else {
+            return Ok(vec![]);
+        };
+
+        let mut tx = begin_scoped(&self.pool, Scope::Tenant(tenant_id)).await?;
+        let rows = sqlx::query("SELECT * FROM tenant_config_values WHERE tenant_id = $1 ORDER BY namespace")
+            .bind(tenant_uuid)
+            .fetch_all(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        rows.iter().map(config_value_from_row).collect()
+    }
+
+    async fn list_namespace_values(&self, namespace: &str) -> Result<Vec<TenantConfigValue>> {
+        let mut tx = begin_scoped(&self.pool, Scope::Platform).await?;
+        let rows = sqlx::query("SELECT * FROM tenant_config_values WHERE namespace = $1")
+            .bind(namespace)
+            .fetch_all(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        rows.iter().map(config_value_from_row).collect()
+    }
+
+    async fn upsert_value(&self, value: &TenantConfigValue) -> Result<TenantConfigValue> {
+        let mut new_value = value.clone();
+        new_value.updated_at = Utc::now();
+
+        let mut tx = begin_scoped(&self.pool, Scope::Tenant(&new_value.tenant_id)).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO tenant_config_values (tenant_id, namespace, value, version, updated_by, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT (tenant_id, namespace) DO UPDATE SET
+                value = EXCLUDED.value,
+                version = EXCLUDED.version,
+                updated_by = EXCLUDED.updated_by,
+                updated_at = EXCLUDED.updated_at
+            "#
+        )
+        .bind(require_id(&new_value.tenant_id, "tenant")?)
+        .bind(&new_value.namespace)
+        .bind(&new_value.value)
+        .bind(new_value.version as i64)
+        .bind(optional_id(&new_value.updated_by, "user")?)
+        .bind(new_value.updated_at)
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(new_value)
+    }
+
+    async fn delete_value(&self, tenant_id: &TenantId, namespace: &str) -> Result<()> {
+        if let Some(tenant_uuid) = parse_id(tenant_id) {
+            let mut tx = begin_scoped(&self.pool, Scope::Tenant(tenant_id)).await?;
+            sqlx::query("DELETE FROM tenant_config_values WHERE tenant_id = $1 AND namespace = $2")
+                .bind(tenant_uuid)
+                .bind(namespace)
+                .execute(&mut *tx)
+                .await?;
+            tx.commit().await?;
+        }
+        Ok(())
+    }
+}
+
+fn archive_from_row(row: &PgRow) -> Result<TenantArchiveRecord> {
+    let requested_at: Option<DateTime<Utc>> = row.try_get("restore_requested_at")?;
+    let sla_deadline: Option<DateTime<Utc>> = row.try_get("restore_sla_deadline")?;
+
+    let restore = match (requested_at, sla_deadline) {
+        (Some(requested_at), Some(sla_deadline)) => Some(TenantRestoreRecord {
+            requested_at,
+            requested_by: optional_string(row.try_get("restore_requested_by")?),
+            sla_deadline,
+            completed_at: row.try_get("restore_completed_at")?,
+            sla_breached: row.try_get("restore_sla_breached")?,
+        }),
+        _ => None,
+    };
+
+    Ok(TenantArchiveRecord {
+        tenant_id: row.try_get::<Uuid, _>("tenant_id")?.to_string(),
+        archive_id: row.try_get::<Uuid, _>("archive_id")?.to_string(),
+        location: row.try_get("location")?,
+        storage_class: row.try_get("storage_class")?,
+        compressed_size_bytes: row.try_get::<i64, _>("compressed_size_bytes")? as u64,
+        uncompressed_size_bytes: row.try_get::<i64, _>("uncompressed_size_bytes")? as u64,
+        checksum: row.try_get("checksum")?,
+        previous_status: status_from_db(row.try_get("previous_status")?)?,
+        reason: row.try_get("reason")?,
+        archived_by: optional_string(row.try_get("archived_by")?),
+        archived_at: row.try_get("archived_at")?,
+        restore,
+    })
+}
+
+pub struct PostgresTenantArchiveRepository {
+    pool: PgPool,
+}
+
+impl PostgresTenantArchiveRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl TenantArchiveRepository for PostgresTenantArchiveRepository {
+    async fn upsert(&self, record: &TenantArchiveRecord) -> Result<TenantArchiveRecord> {
+        let restore = record.restore.as_ref();
+
+        let mut tx = begin_scoped(&self.pool, Scope::Tenant(&record.tenant_id)).await?;
+        sqlx::query(
+            r#"
+            INSERT INTO tenant_archives (
+                tenant_id, archive_id, location, storage_class, compressed_size_bytes,
+                uncompressed_size_bytes, checksum, previous_status, reason, archived_by,
+                archived_at, restore_requested_at, restore_requested_by, restore_sla_deadline,
+                restore_completed_at, restore_sla_breached
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16)
+            ON CONFLICT (tenant_id) DO UPDATE SET
+                archive_id = EXCLUDED.archive_id,
+                location = EXCLUDED.location,
+                storage_class = EXCLUDED.storage_class,
+                compressed_size_bytes = EXCLUDED.compressed_size_bytes,
+                uncompressed_size_bytes = EXCLUDED.uncompressed_size_bytes,
+                checksum = EXCLUDED.checksum,
+                previous_status = EXCLUDED.previous_status,
+                reason = EXCLUDED.reason,
+                archived_by = EXCLUDED.archived_by,
+                archived_at = EXCLUDED.archived_at,
+                restore_requested_at = EXCLUDED.restore_requested_at,
+                restore_requested_by = EXCLUDED.restore_requested_by,
+                restore_sla_deadline = EXCLUDED.restore_sla_deadline,
+                restore_completed_at = EXCLUDED.restore_completed_at,
+                restore_sla_breached = EXCLUDED.restore_sla_breached
+            "#
+        )
+        .bind(require_id(&record.tenant_id, "tenant")?)
+        .bind(require_id(&record.archive_id, "archive")?)
+        .bind(&record.location)
+        .bind(&record.storage_class)
+        .bind(record.compressed_size_bytes as i64)
+        .bind(record.uncompressed_size_bytes as i64)
+        .bind(&record.checksum)
+        .bind(status_to_db(&record.previous_status))
+        .bind(&record.reason)
+        .bind(optional_id(&record.archived_by, "user")?)
+        .bind(record.archived_at)
+        .bind(restore.map(|r| r.requested_at))
+        .bind(restore.map(|r| optional_id(&r.requested_by, "user")).transpose()?.flatten())
+        .bind(restore.map(|r| r.sla_deadline))
+        .bind(restore.and_then(|r| r.completed_at))
+        .bind(restore.map(|r| r.sla_breached).unwrap_or(false))
+        .execute(&mut *tx)
+        .await?;
+        tx.commit().await?;
+
+        Ok(record.clone())
+    }
+
+    async fn find_by_tenant(&self, tenant_id: &TenantId) -> Result<Option<TenantArchiveRecord>> {
+        let Some(tenant_uuid) = parse_id(tenant_id) else {
+            return Ok(None);
+        };
+
+        let mut tx = begin_scoped(&self.pool, Scope::Tenant(tenant_id)).await?;
+        let row = sqlx::query("SELECT * FROM tenant_archives WHERE tenant_id = $1")
+            .bind(tenant_uuid)
+            .fetch_optional(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        row.as_ref().map(archive_from_row).transpose()
+    }
+
+    async fn list(&self) -> Result<Vec<TenantArchiveRecord>> {
+        let mut tx = begin_scoped(&self.pool, Scope::Platform).await?;
+        let rows = sqlx::query("SELECT * FROM tenant_archives ORDER BY archived_at DESC")
+            .fetch_all(&mut *tx)
+            .await?;
+        tx.commit().await?;
+
+        rows.iter().map(archive_from_row).collect()
+    }
+}
+
+fn deletion_from_row(row: &PgRow) -> Result<TenantDeletionRecord> {
+    let stage: String = row.try_get("stage")?;
+
+    Ok(TenantDeletionRecord {
+        tenant_id: row.try_get::<Uuid, _>("tenant_id")?.to_string(),
+        tenant_name: row.try_get("tenant_name")?,
+        stage: serde_json::from_value(serde_json::Value::String(stage))?,
+        previous_status: status_from_db(row.try_get("previous_status")?)?,
+        requested_by: row.try_get::<Uuid, _>("requested_by")?.to_string(),
+        reason: row.try_get("reason")?,
+        soft_deleted_at: row.try_get("soft_deleted_at")?,
+        purge_after: row.try_get("purge_after")?,
+        purged_services: serde_json::from_value(row.try_get("purged_services")?)?,
+        purged_at: row.try_get("purged_at")?,
+        certificate_id: optional_string(row.try_get("certificate_id")?),
+        cancelled_at: row.try_get("cancelled_at")?,
+    })
+}
+
+/// Deletion records have no RLS policy: they outlive the tenant and only
+/// platform admins read them
+pub struct PostgresTenantDeletionRepository {
+    pool: PgPool,
+}
+
+impl PostgresTenantDeletionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl TenantDeletionRepository for PostgresTenantDeletionRepository {
+    async fn upsert(&self, record: &TenantDeletionRecord) -> Result<TenantDeletionRecord> {
+        let stage = serde_json::to_value(&record.stage)?;
+
+        sqlx::query(
+            r#"
+            INSERT INTO tenant_deletions (
+                tenant_id, tenant_name, stage, previous_status, requested_by, reason,
+                soft_deleted_at, purge_after, purged_services, purged_at, certificate_id,
+                cancelled_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)
+            ON CONFLICT (tenant_id) DO UPDATE SET
+                tenant_name = EXCLUDED.tenant_name,
+                stage = EXCLUDED.stage,
+                previous_status = EXCLUDED.previous_status,
+                requested_by = EXCLUDED.requested_by,
+                reason = EXCLUDED.reason,
+                soft_deleted_at = EXCLUDED.soft_deleted_at,
+                purge_after = EXCLUDED.purge_after,
+                purged_services = EXCLUDED.purged_services,
+                purged_at = EXCLUDED.purged_at,
+                certificate_id = EXCLUDED.certificate_id,
+                cancelled_at = EXCLUDED.cancelled_at
+            "#
+        )
+        .bind(require_id(&record.tenant_id, "tenant")?)
+        .bind(&record.tenant_name)
+        .bind(stage.as_str().unwrap_or_default())
+        .bind(status_to_db(&record.previous_status))
+        .bind(require_id(&record.requested_by, "user")?)
+        .bind(&record.reason)
+        .bind(record.soft_deleted_at)
+        .bind(record.purge_after)
+        .bind(serde_json::to_value(&record.purged_services)?)
+        .bind(record.purged_at)
+        .bind(optional_id(&record.certificate_id, "certificate")?)
+        .bind(record.cancelled_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(record.clone())
+    }
+
+    async fn find_by_tenant(&self, tenant_id: &TenantId) -> Result<Option<TenantDeletionRecord>> {
+        let Some(tenant_uuid) = parse_id(tenant_id) else {
+            return Ok(None);
+        };
+
+        let row = sqlx::query("SELECT * FROM tenant_deletions WHERE tenant_id = $1")
+            .bind(tenant_uuid)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(deletion_from_row).transpose()
+    }
+
+    async fn list(&self) -> Result<Vec<TenantDeletionRecord>> {
+        let rows = sqlx::query("SELECT * FROM tenant_deletions ORDER BY soft_deleted_at DESC")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(deletion_from_row).collect()
+    }
+}
+
+fn event_from_row(row: &PgRow) -> Result<TenantLifecycleEvent> {
+    Ok(TenantLifecycleEvent {
+        event_id: row.try_get::<Uuid, _>("event_id")?.to_string(),
+        sequence: row.try_get::<i64, _>("sequence")? as u64,
+        event_type: event_type_from_db(row.try_get("event_type")?)?,
+        tenant_id: row.try_get::<Uuid, _>("tenant_id")?.to_string(),
+        tenant_name: row.try_get("tenant_name")?,
+        subscription_tier: tier_from_db(row.try_get("subscription_tier")?)?,
+        status: status_from_db(row.try_get("status")?)?,
+        data: row.try_get("data")?,
+        occurred_at: row.try_get("occurred_at")?,
+    })
+}
+
+fn webhook_from_row(row: &PgRow) -> Result<TenantWebhook> {
+    let event_types: Vec<String> = row.try_get("event_types")?;
+
+    Ok(TenantWebhook {
+        id: row.try_get::<Uuid, _>("id")?.to_string(),
+        url: row.try_get("url")?,
+        secret: row.try_get("secret")?,
+        previous_secret: row.try_get("previous_secret")?,
+        previous_secret_expires_at: row.try_get("previous_secret_expires_at")?,
+        secret_rotated_at: row.try_get("secret_rotated_at")?,
+        description: row.try_get("description")?,
+        event_types: event_types.iter().map(|t| event_type_from_db(t)).collect::<Result<_>>()?,
+        is_active: row.try_get("is_active")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+fn delivery_from_row(row: &PgRow) -> Result<WebhookDelivery> {
+    Ok(WebhookDelivery {
+        id: row.try_get::<Uuid, _>("id")?.to_string(),
+        webhook_id: row.try_get::<Uuid, _>("webhook_id")?.to_string(),
+        event_id: row.try_get::<Uuid, _>("event_id")?.to_string(),
+        event_type: event_type_from_db(row.try_get("event_type")?)?,
+        attempt: row.try_get::<i32, _>("attempt")? as u32,
+        status_code: row.try_get::<Option<i32>, _>("status_code")?.map(|code| code as u16),
+        success: row.try_get("success")?,
+        error: row.try_get("error")?,
+        attempted_at: row.try_get("attempted_at")?,
+    })
+}
+
+/// The event log and webhooks are platform tables without RLS
+pub struct PostgresLifecycleEventRepository {
+    pool: PgPool,
+}
+
+impl PostgresLifecycleEventRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl LifecycleEventRepository for PostgresLifecycleEventRepository {
+    async fn append_event(&self, event: &TenantLifecycleEvent) -> Result<TenantLifecycleEvent> {
+        let sequence: i64 = sqlx::query_scalar(
+            r#"
+            INSERT INTO tenant_lifecycle_events (
+                event_id, event_type, tenant_id, tenant_name, subscription_tier, status, data, occurred_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            RETURNING sequence
+            "#
+        )
+        .bind(require_id(&event.event_id, "event")?)
+        .bind(event.event_type.as_str())
+        .bind(require_id(&event.tenant_id, "tenant")?)
+        .bind(&event.tenant_name)
+        .bind(tier_to_db(&event.subscription_tier))
+        .bind(status_to_db(&event.status))
+        .bind(&event.data)
+        .bind(event.occurred_at)
+        .fetch_one(&self.pool)
+        .await?;
+
+        let mut new_event = event.clone();
+        new_event.sequence = sequence as u64;
+        Ok(new_event)
+    }
+
+    async fn list_events(&self, after_sequence: u64, tenant_id: Option<&TenantId>, limit: u32) -> Result<Vec<TenantLifecycleEvent>> {
+        let tenant_uuid = match tenant_id {
+            Some(tenant_id) => match parse_id(tenant_id) {
+                Some(tenant_uuid) => Some(tenant_uuid),
+                None => return Ok(vec![]),
+            },
+            None => None,
+        };
+
+        let rows = sqlx::query(
+            r#"
+            SELECT * FROM tenant_lifecycle_events
+            WHERE sequence > $1 AND ($2::uuid IS NULL OR tenant_id = $2)
+            ORDER BY sequence
+            LIMIT $3
+            "#
         )
+        .bind(after_sequence as i64)
+        .bind(tenant_uuid)
+        .bind(limit as i64)
         .fetch_all(&self.pool)
         .await?;
 
-        let mut memberships = Vec::new();
-        for row in rows {
-            let membership = TenantMembership {
-                id: row.id,
-                tenant_id: row.tenant_id,
-                user_id: row.user_id,
-                role: serde_json::from_str(&row.role)?,
-                permissions: row.permissions,
-                status: serde_json::from_str(&row.status)?,
-                invited_by: row.invited_by,
-                invited_at: row.invited_at,
-                joined_at: row.joined_at,
-                created_at: row.created_at,
-                updated_at: row.updated_at,
-            };
-            memberships.push(membership);
+        rows.iter().map(event_from_row).collect()
+    }
+
+    async fn create_webhook(&self, webhook: &TenantWebhook) -> Result<TenantWebhook> {
+        let mut new_webhook = webhook.clone();
+        if new_webhook.id.is_empty() {
+            new_webhook.id = Uuid::new_v4().to_string();
         }
 
-        Ok(memberships)
+        let event_types: Vec<&str> = new_webhook.event_types.iter().map(|t| t.as_str()).collect();
+        sqlx::query(
+            r#"
+            INSERT INTO tenant_lifecycle_webhooks (id, url, secret, description, event_types, is_active, created_at, updated_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#
+        )
+        .bind(require_id(&new_webhook.id, "webhook")?)
+        .bind(&new_webhook.url)
+        .bind(&new_webhook.secret)
+        .bind(&new_webhook.description)
+        .bind(&event_types)
+        .bind(new_webhook.is_active)
+        .bind(new_webhook.created_at)
+        .bind(new_webhook.updated_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(new_webhook)
     }
 
-    async fn update(&self, membership: &TenantMembership) -> Result<TenantMembership> {
-        let mut updated_membership = membership.clone();
-        updated_membership.updated_at = Utc::now();
+    async fn find_webhook(&self, id: &str) -> Result<Option<TenantWebhook>> {
+        let Some(id) = parse_id(id) else {
+            return Ok(None);
+        };
+
+        let row = sqlx::query("SELECT * FROM tenant_lifecycle_webhooks WHERE id = $1")
+            .bind(id)
+            .fetch_optional(&self.pool)
+            .await?;
+
+        row.as_ref().map(webhook_from_row).transpose()
+    }
+
+    async fn list_webhooks(&self) -> Result<Vec<TenantWebhook>> {
+        let rows = sqlx::query("SELECT * FROM tenant_lifecycle_webhooks ORDER BY created_at")
+            .fetch_all(&self.pool)
+            .await?;
+
+        rows.iter().map(webhook_from_row).collect()
+    }
 
-        sqlx::query!(
+    async fn update_webhook(&self, webhook: &TenantWebhook) -> Result<TenantWebhook> {
+        let mut updated_webhook = webhook.clone();
+        updated_webhook.updated_at = Utc::now();
+
+        let event_types: Vec<&str> = updated_webhook.event_types.iter().map(|t| t.as_str()).collect();
+        let result = sqlx::query(
             r#"
-            UPDATE tenant_memberships SET
-                role = $2,
-                permissions = $3,
-                status = $4,
-                joined_at = $5,
-                updated_at = $6
+            UPDATE tenant_lifecycle_webhooks SET
+                url = $2,
+                secret = $3,
+                description = $4,
+                event_types = $5,
+                is_active = $6,
+                updated_at = $7,
+                previous_secret = $8,
+                previous_secret_expires_at = $9,
+                secret_rotated_at = $10
             WHERE id = $1
-            "#,
-            updated_membership.id,
-            serde_json::to_string(&updated_membership.role)?,
-            &updated_membership.permissions,
-            serde_json::to_string(&updated_membership.status)?,
-            updated_membership.joined_at,
-            updated_membership.updated_at
+            "#
         )
+        .bind(parse_id(&updated_webhook.id))
+        .bind(&updated_webhook.url)
+        .bind(&updated_webhook.secret)
+        .bind(&updated_webhook.description)
+        .bind(&event_types)
+        .bind(updated_webhook.is_active)
+        .bind(updated_webhook.updated_at)
+        .bind(&updated_webhook.previous_secret)
+        .bind(updated_webhook.previous_secret_expires_at)
+        .bind(updated_webhook.secret_rotated_at)
         .execute(&self.pool)
         .await?;
 
-        Ok(updated_membership)
+        if result.rows_affected() == 0 {
+            return Err(anyhow!("Webhook not found"));
+        }
+
+        Ok(updated_webhook)
     }
 
-    async fn delete(&self, id: &str) -> Result<()> {
-        sqlx::query!(
-            "DELETE FROM tenant_memberships WHERE id = $1",
-            id
+    async fn delete_webhook(&self, id: &str) -> Result<()> {
+        // Deliveries go with the webhook through ON DELETE CASCADE
+        if let Some(id) = parse_id(id) {
+            sqlx::query("DELETE FROM tenant_lifecycle_webhooks WHERE id = $1")
+                .bind(id)
+                .execute(&self.pool)
+                .await?;
+        }
+        Ok(())
+    }
+
+    async fn record_delivery(&self, delivery: &WebhookDelivery) -> Result<()> {
+        sqlx::query(
+            r#"
+            INSERT INTO tenant_lifecycle_webhook_deliveries (
+                id, webhook_id, event_id, event_type, attempt, status_code, success, error, attempted_at
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            "#
         )
+        .bind(require_id(&delivery.id, "delivery")?)
+        .bind(require_id(&delivery.webhook_id, "webhook")?)
+        .bind(require_id(&delivery.event_id, "event")?)
+        .bind(delivery.event_type.as_str())
+        .bind(delivery.attempt as i32)
+        .bind(delivery.status_code.map(|code| code as i32))
+        .bind(delivery.success)
+        .bind(&delivery.error)
+        .bind(delivery.attempted_at)
         .execute(&self.pool)
         .await?;
 
         Ok(())
     }
-}
\ No newline at end of file
+
+    async fn list_deliveries(&self, webhook_id: &str, limit: u32) -> Result<Vec<WebhookDelivery>> {
+        let Some(webhook_id) = parse_id(webhook_id) else {
+            return Ok(vec![]);
+        };
+
+        let rows = sqlx::query(
+            "SELECT * FROM tenant_lifecycle_webhook_deliveries WHERE webhook_id = $1 ORDER BY attempted_at DESC LIMIT $2"
+        )
+        .bind(webhook_id)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(delivery_from_row).collect()
+    }
+}
+
+/// Inserts the built-in templates, config namespaces and template feature
+/// flags that are missing, leaving rows an operator has edited alone. Runs
+/// on every startup; the in-memory repositories seed themselves instead.
+pub async fn seed_builtin_records(pool: &PgPool) -> Result<()> {
+    let templates = PostgresTenantTemplateRepository::new(pool.clone());
+    let flags = PostgresFeatureFlagRepository::new(pool.clone());
+    let config = PostgresTenantConfigRepository::new(pool.clone());
+    let now = Utc::now();
+
+    for template in builtin_templates() {
+        for feature in template.features.iter().filter(|feature| feature.as_str() != ALL_FEATURES) {
+            if flags.find_flag(feature).await?.is_none() {
+                flags.create_flag(&FeatureFlag {
+                    key: feature.clone(),
+                    description: None,
+                    default_enabled: false,
+                    enabled_tiers: Vec::new(),
+                    rollout_percentage: 0,
+                    is_active: true,
+                    created_at: now,
+                    updated_at: now,
+                }).await?;
+            }
+        }
+
+        if templates.find_by_name(&template.name).await?.is_none() {
+            templates.create(&template).await?;
+        }
+    }
+
+    for namespace in builtin_namespaces() {
+        if config.find_namespace(&namespace.name).await?.is_none() {
+            config.create_namespace(&namespace).await?;
+        }
+    }
+
+    Ok(())
+}
@@ -6,7 +6,13 @@ use std::collections::HashMap;
 use std::sync::{Arc, Mutex};
 
 use crate::models::*;
-use crate::repository_traits::{TenantRepository, TenantMembershipRepository};
+use crate::feature_flags::ALL_FEATURES;
+use crate::lifecycle_events::TenantLifecycleEvent;
+use crate::repository_traits::{
+    FeatureFlagRepository, LifecycleEventRepository, TenantArchiveRepository, TenantConfigRepository, TenantDeletionRepository, TenantRepository, TenantMembershipRepository, TenantTemplateRepository,
+};
+use crate::templates::builtin_templates;
+use crate::tenant_config::builtin_namespaces;
 use adx_shared::types::{TenantId, UserId};
 
 // Simple in-memory implementation for development/testing
@@ -83,6 +89,16 @@ impl TenantRepository for SimpleTenantRepository {
         Ok(tenant_list[offset..end].to_vec())
     }
 
+    async fn list_children(&self, parent_id: &TenantId) -> Result<Vec<Tenant>> {
+        let tenants = self.tenants.lock().unwrap();
+        let mut children: Vec<Tenant> = tenants.values()
+            .filter(|t| t.parent_tenant_id.as_ref() == Some(parent_id))
+            .cloned()
+            .collect();
+        children.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        Ok(children)
+    }
+
     async fn update(&self, tenant: &Tenant) -> Result<Tenant> {
         let mut updated_tenant = tenant.clone();
         updated_tenant.updated_at = Utc::now();
@@ -180,4 +196,436 @@ impl TenantMembershipRepository for SimpleTenantMembershipRepository {
         memberships.remove(id);
         Ok(())
     }
-}
\ No newline at end of file
+}
+pub struct SimpleTenantTemplateRepository {
+    templates: Arc<Mutex<HashMap<String, TenantTemplate>>>,
+}
+
+impl SimpleTenantTemplateRepository {
+    /// Creates a repository seeded with the built-in plan templates
+    pub fn new() -> Self {
+        let templates = builtin_templates()
+            .into_iter()
+            .map(|template| (template.name.clone(), template))
+            .collect();
+
+        Self {
+            templates: Arc::new(Mutex::new(templates)),
+        }
+    }
+}
+
+#[async_trait]
+impl TenantTemplateRepository for SimpleTenantTemplateRepository {
+    async fn create(&self, template: &TenantTemplate) -> Result<TenantTemplate> {
+        let mut new_template = template.clone();
+        new_template.created_at = Utc::now();
+        new_template.updated_at = Utc::now();
+
+        let mut templates = self.templates.lock().unwrap();
+        templates.insert(new_template.name.clone(), new_template.clone());
+
+        Ok(new_template)
+    }
+
+    async fn find_by_name(&self, name: &str) -> Result<Option<TenantTemplate>> {
+        let templates = self.templates.lock().unwrap();
+        Ok(templates.get(name).cloned())
+    }
+
+    async fn list(&self, include_inactive: bool) -> Result<Vec<TenantTemplate>> {
+        let templates = self.templates.lock().unwrap();
+        let mut template_list: Vec<TenantTemplate> = templates.values()
+            .filter(|t| include_inactive || t.is_active)
+            .cloned()
+            .collect();
+        template_list.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(template_list)
+    }
+
+    async fn update(&self, template: &TenantTemplate) -> Result<TenantTemplate> {
+        let mut updated_template = template.clone();
+        updated_template.updated_at = Utc::now();
+
+        let mut templates = self.templates.lock().unwrap();
+        templates.insert(updated_template.name.clone(), updated_template.clone());
+
+        Ok(updated_template)
+    }
+
+    async fn delete(&self, name: &str) -> Result<()> {
+        let mut templates = self.templates.lock().unwrap();
+        templates.remove(name);
+        Ok(())
+    }
+}
+
+pub struct SimpleFeatureFlagRepository {
+    flags: Arc<Mutex<HashMap<String, FeatureFlag>>>,
+    overrides: Arc<Mutex<HashMap<(TenantId, String), TenantFlagOverride>>>,
+}
+
+impl SimpleFeatureFlagRepository {
+    /// Creates a repository with a flag for every feature the built-in
+    /// templates grant; plans keep enabling them through tenant features
+    pub fn new() -> Self {
+        let now = Utc::now();
+        let flags = builtin_templates()
+            .into_iter()
+            .flat_map(|template| template.features)
+            .filter(|feature| feature != ALL_FEATURES)
+            .map(|key| {
+                let flag = FeatureFlag {
+                    key: key.clone(),
+                    description: None,
+                    default_enabled: false,
+                    enabled_tiers: Vec::new(),
+                    rollout_percentage: 0,
+                    is_active: true,
+                    created_at: now,
+                    updated_at: now,
+                };
+                (key, flag)
+            })
+            .collect();
+
+        Self {
+            flags: Arc::new(Mutex::new(flags)),
+            overrides: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl FeatureFlagRepository for SimpleFeatureFlagRepository {
+    async fn create_flag(&self, flag: &FeatureFlag) -> Result<FeatureFlag> {
+        let mut new_flag = flag.clone();
+        new_flag.created_at = Utc::now();
+        new_flag.updated_at = Utc::now();
+
+        let mut flags = self.flags.lock().unwrap();
+        flags.insert(new_flag.key.clone(), new_flag.clone());
+
+        Ok(new_flag)
+    }
+
+    async fn find_flag(&self, key: &str) -> Result<Option<FeatureFlag>> {
+        let flags = self.flags.lock().unwrap();
+        Ok(flags.get(key).cloned())
+    }
+
+    async fn list_flags(&self, include_inactive: bool) -> Result<Vec<FeatureFlag>> {
+        let flags = self.flags.lock().unwrap();
+        let mut flag_list: Vec<FeatureFlag> = flags.values()
+            .filter(|f| include_inactive || f.is_active)
+            .cloned()
+            .collect();
+        flag_list.sort_by(|a, b| a.key.cmp(&b.key));
+        Ok(flag_list)
+    }
+
+    async fn update_flag(&self, flag: &FeatureFlag) -> Result<FeatureFlag> {
+        let mut updated_flag = flag.clone();
+        updated_flag.updated_at = Utc::now();
+
+        let mut flags = self.flags.lock().unwrap();
+        flags.insert(updated_flag.key.clone(), updated_flag.clone());
+
+        Ok(updated_flag)
+    }
+
+    async fn delete_flag(&self, key: &str) -> Result<()> {
+        self.flags.lock().unwrap().remove(key);
+        self.overrides.lock().unwrap().retain(|(_, flag_key), _| flag_key != key);
+        Ok(())
+    }
+
+    async fn set_override(&self, tenant_override: &TenantFlagOverride) -> Result<TenantFlagOverride> {
+        let mut new_override = tenant_override.clone();
+        new_override.updated_at = Utc::now();
+
+        let mut overrides = self.overrides.lock().unwrap();
+        overrides.insert(
+            (new_override.tenant_id.clone(), new_override.flag_key.clone()),
+            new_override.clone(),
+        );
+
+        Ok(new_override)
+    }
+
+    async fn list_overrides(&self, tenant_id: &TenantId) -> Result<Vec<TenantFlagOverride>> {
+        let overrides = self.overrides.lock().unwrap();
+        let mut override_list: Vec<TenantFlagOverride> = overrides.values()
+            .filter(|o| &o.tenant_id == tenant_id)
+            .cloned()
+            .collect();
+        override_list.sort_by(|a, b| a.flag_key.cmp(&b.flag_key));
+        Ok(override_list)
+    }
+
+    async fn delete_override(&self, tenant_id: &TenantId, key: &str) -> Result<()> {
+        let mut overrides = self.overrides.lock().unwrap();
+        overrides.remove(&(tenant_id.clone(), key.to_string()));
+        Ok(())
+    }
+}
+
+pub struct SimpleTenantConfigRepository {
+    namespaces: Arc<Mutex<HashMap<String, ConfigNamespace>>>,
+    values: Arc<Mutex<HashMap<(TenantId, String), TenantConfigValue>>>,
+}
+
+impl SimpleTenantConfigRepository {
+    /// Creates a repository seeded with the built-in namespaces
+    pub fn new() -> Self {
+        let namespaces = builtin_namespaces()
+            .into_iter()
+            .map(|namespace| (namespace.name.clone(), namespace))
+            .collect();
+
+        Self {
+            namespaces: Arc::new(Mutex::new(namespaces)),
+            values: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl TenantConfigRepository for SimpleTenantConfigRepository {
+    async fn create_namespace(&self, namespace: &ConfigNamespace) -> Result<ConfigNamespace> {
+        let mut new_namespace = namespace.clone();
+        new_namespace.created_at = Utc::now();
+        new_namespace.updated_at = Utc::now();
+
+        let mut namespaces = self.namespaces.lock().unwrap();
+        namespaces.insert(new_namespace.name.clone(), new_namespace.clone());
+
+        Ok(new_namespace)
+    }
+
+    async fn find_namespace(&self, name: &str) -> Result<Option<ConfigNamespace>> {
+        let namespaces = self.namespaces.lock().unwrap();
+        Ok(namespaces.get(name).cloned())
+    }
+
+    async fn list_namespaces(&self) -> Result<Vec<ConfigNamespace>> {
+        let namespaces = self.namespaces.lock().unwrap();
+        let mut namespace_list: Vec<ConfigNamespace> = namespaces.values().cloned().collect();
+        namespace_list.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(namespace_list)
+    }
+
+    async fn update_namespace(&self, namespace: &ConfigNamespace) -> Result<ConfigNamespace> {
+        let mut updated_namespace = namespace.clone();
+        updated_namespace.updated_at = Utc::now();
+
+        let mut namespaces = self.namespaces.lock().unwrap();
+        namespaces.insert(updated_namespace.name.clone(), updated_namespace.clone());
+
+        Ok(updated_namespace)
+    }
+
+    async fn delete_namespace(&self, name: &str) -> Result<()> {
+        self.namespaces.lock().unwrap().remove(name);
+        self.values.lock().unwrap().retain(|(_, namespace), _| namespace != name);
+        Ok(())
+    }
+
+    async fn find_value(&self, tenant_id: &TenantId, namespace: &str) -> Result<Option<TenantConfigValue>> {
+        let values = self.values.lock().unwrap();
+        Ok(values.get(&(tenant_id.clone(), namespace.to_string())).cloned())
+    }
+
+    async fn list_values(&self, tenant_id: &TenantId) -> Result<Vec<TenantConfigValue>> {
+        let values = self.values.lock().unwrap();
+        let mut value_list: Vec<TenantConfigValue> = values.values()
+            .filter(|v| &v.tenant_id == tenant_id)
+            .cloned()
+            .collect();
+        value_list.sort_by(|a, b| a.namespace.cmp(&b.namespace));
+        Ok(value_list)
+    }
+
+    async fn list_namespace_values(&self, namespace: &str) -> Result<Vec<TenantConfigValue>> {
+        let values = self.values.lock().unwrap();
+        Ok(values.values().filter(|v| v.namespace == namespace).cloned().collect())
+    }
+
+    async fn upsert_value(&self, value: &TenantConfigValue) -> Result<TenantConfigValue> {
+        let mut new_value = value.clone();
+        new_value.updated_at = Utc::now();
+
+        let mut values = self.values.lock().unwrap();
+        values.insert((new_value.tenant_id.clone(), new_value.namespace.clone()), new_value.clone());
+
+        Ok(new_value)
+    }
+
+    async fn delete_value(&self, tenant_id: &TenantId, namespace: &str) -> Result<()> {
+        let mut values = self.values.lock().unwrap();
+        values.remove(&(tenant_id.clone(), namespace.to_string()));
+        Ok(())
+    }
+}
+
+pub struct SimpleTenantArchiveRepository {
+    records: Arc<Mutex<HashMap<TenantId, TenantArchiveRecord>>>,
+}
+
+impl SimpleTenantArchiveRepository {
+    pub fn new() -> Self {
+        Self {
+            records: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl TenantArchiveRepository for SimpleTenantArchiveRepository {
+    async fn upsert(&self, record: &TenantArchiveRecord) -> Result<TenantArchiveRecord> {
+        let mut records = self.records.lock().unwrap();
+        records.insert(record.tenant_id.clone(), record.clone());
+        Ok(record.clone())
+    }
+
+    async fn find_by_tenant(&self, tenant_id: &TenantId) -> Result<Option<TenantArchiveRecord>> {
+        let records = self.records.lock().unwrap();
+        Ok(records.get(tenant_id).cloned())
+    }
+
+    async fn list(&self) -> Result<Vec<TenantArchiveRecord>> {
+        let records = self.records.lock().unwrap();
+        let mut record_list: Vec<TenantArchiveRecord> = records.values().cloned().collect();
+        record_list.sort_by(|a, b| b.archived_at.cmp(&a.archived_at));
+        Ok(record_list)
+    }
+}
+
+pub struct SimpleTenantDeletionRepository {
+    records: Arc<Mutex<HashMap<TenantId, TenantDeletionRecord>>>,
+}
+
+impl SimpleTenantDeletionRepository {
+    pub fn new() -> Self {
+        Self {
+            records: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl TenantDeletionRepository for SimpleTenantDeletionRepository {
+    async fn upsert(&self, record: &TenantDeletionRecord) -> Result<TenantDeletionRecord> {
+        let mut records = self.records.lock().unwrap();
+        records.insert(record.tenant_id.clone(), record.clone());
+        Ok(record.clone())
+    }
+
+    async fn find_by_tenant(&self, tenant_id: &TenantId) -> Result<Option<TenantDeletionRecord>> {
+        let records = self.records.lock().unwrap();
+        Ok(records.get(tenant_id).cloned())
+    }
+
+    async fn list(&self) -> Result<Vec<TenantDeletionRecord>> {
+        let records = self.records.lock().unwrap();
+        let mut record_list: Vec<TenantDeletionRecord> = records.values().cloned().collect();
+        record_list.sort_by(|a, b| b.soft_deleted_at.cmp(&a.soft_deleted_at));
+        Ok(record_list)
+    }
+}
+
+pub struct SimpleLifecycleEventRepository {
+    events: Arc<Mutex<Vec<TenantLifecycleEvent>>>,
+    webhooks: Arc<Mutex<HashMap<String, TenantWebhook>>>,
+    deliveries: Arc<Mutex<Vec<WebhookDelivery>>>,
+}
+
+impl SimpleLifecycleEventRepository {
+    pub fn new() -> Self {
+        Self {
+            events: Arc::new(Mutex::new(Vec::new())),
+            webhooks: Arc::new(Mutex::new(HashMap::new())),
+            deliveries: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+}
+
+#[async_trait]
+impl LifecycleEventRepository for SimpleLifecycleEventRepository {
+    async fn append_event(&self, event: &TenantLifecycleEvent) -> Result<TenantLifecycleEvent> {
+        let mut events = self.events.lock().unwrap();
+        let mut new_event = event.clone();
+        new_event.sequence = events.last().map(|e| e.sequence).unwrap_or(0) + 1;
+        events.push(new_event.clone());
+        Ok(new_event)
+    }
+
+    async fn list_events(&self, after_sequence: u64, tenant_id: Option<&TenantId>, limit: u32) -> Result<Vec<TenantLifecycleEvent>> {
+        let events = self.events.lock().unwrap();
+        Ok(events
+            .iter()
+            .filter(|e| e.sequence > after_sequence)
+            .filter(|e| tenant_id.is_none() || tenant_id == Some(&e.tenant_id))
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+
+    async fn create_webhook(&self, webhook: &TenantWebhook) -> Result<TenantWebhook> {
+        let mut webhooks = self.webhooks.lock().unwrap();
+        let mut new_webhook = webhook.clone();
+        if new_webhook.id.is_empty() {
+            new_webhook.id = Uuid::new_v4().to_string();
+        }
+        webhooks.insert(new_webhook.id.clone(), new_webhook.clone());
+        Ok(new_webhook)
+    }
+
+    async fn find_webhook(&self, id: &str) -> Result<Option<TenantWebhook>> {
+        let webhooks = self.webhooks.lock().unwrap();
+        Ok(webhooks.get(id).cloned())
+    }
+
+    async fn list_webhooks(&self) -> Result<Vec<TenantWebhook>> {
+        let webhooks = self.webhooks.lock().unwrap();
+        let mut webhook_list: Vec<TenantWebhook> = webhooks.values().cloned().collect();
+        webhook_list.sort_by(|a, b| a.created_at.cmp(&b.created_at));
+        Ok(webhook_list)
+    }
+
+    async fn update_webhook(&self, webhook: &TenantWebhook) -> Result<TenantWebhook> {
+        let mut webhooks = self.webhooks.lock().unwrap();
+        if !webhooks.contains_key(&webhook.id) {
+            return Err(anyhow::anyhow!("Webhook not found"));
+        }
+        let mut updated_webhook = webhook.clone();
+        updated_webhook.updated_at = Utc::now();
+        webhooks.insert(updated_webhook.id.clone(), updated_webhook.clone());
+        Ok(updated_webhook)
+    }
+
+    async fn delete_webhook(&self, id: &str) -> Result<()> {
+        let mut webhooks = self.webhooks.lock().unwrap();
+        webhooks.remove(id);
+        let mut deliveries = self.deliveries.lock().unwrap();
+        deliveries.retain(|d| d.webhook_id != id);
+        Ok(())
+    }
+
+    async fn record_delivery(&self, delivery: &WebhookDelivery) -> Result<()> {
+        let mut deliveries = self.deliveries.lock().unwrap();
+        deliveries.push(delivery.clone());
+        Ok(())
+    }
+
+    async fn list_deliveries(&self, webhook_id: &str, limit: u32) -> Result<Vec<WebhookDelivery>> {
+        let deliveries = self.deliveries.lock().unwrap();
+        Ok(deliveries
+            .iter()
+            .rev()
+            .filter(|d| d.webhook_id == webhook_id)
+            .take(limit as usize)
+            .cloned()
+            .collect())
+    }
+}
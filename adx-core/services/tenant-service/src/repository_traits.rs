@@ -1,6 +1,7 @@
 use async_trait::async_trait;
 use anyhow::Result;
 
+use crate::lifecycle_events::TenantLifecycleEvent;
 use crate::models::*;
 use adx_shared::types::{TenantId, UserId};
 
@@ -11,6 +12,7 @@ pub trait TenantRepository: Send + Sync {
     async fn find_by_slug(&self, slug: &str) -> Result<Option<Tenant>>;
     async fn find_by_name(&self, name: &str) -> Result<Option<Tenant>>;
     async fn list(&self, limit: Option<u32>, offset: Option<u32>) -> Result<Vec<Tenant>>;
+    async fn list_children(&self, parent_id: &TenantId) -> Result<Vec<Tenant>>;
     async fn update(&self, tenant: &Tenant) -> Result<Tenant>;
     async fn delete(&self, id: &TenantId) -> Result<()>;
     async fn count(&self) -> Result<u64>;
@@ -25,4 +27,70 @@ pub trait TenantMembershipRepository: Send + Sync {
     async fn list_by_user(&self, user_id: &UserId) -> Result<Vec<TenantMembership>>;
     async fn update(&self, membership: &TenantMembership) -> Result<TenantMembership>;
     async fn delete(&self, id: &str) -> Result<()>;
-}
\ No newline at end of file
+}
+#[async_trait]
+pub trait TenantTemplateRepository: Send + Sync {
+    async fn create(&self, template: &TenantTemplate) -> Result<TenantTemplate>;
+    async fn find_by_name(&self, name: &str) -> Result<Option<TenantTemplate>>;
+    async fn list(&self, include_inactive: bool) -> Result<Vec<TenantTemplate>>;
+    async fn update(&self, template: &TenantTemplate) -> Result<TenantTemplate>;
+    async fn delete(&self, name: &str) -> Result<()>;
+}
+
+#[async_trait]
+pub trait FeatureFlagRepository: Send + Sync {
+    async fn create_flag(&self, flag: &FeatureFlag) -> Result<FeatureFlag>;
+    async fn find_flag(&self, key: &str) -> Result<Option<FeatureFlag>>;
+    async fn list_flags(&self, include_inactive: bool) -> Result<Vec<FeatureFlag>>;
+    async fn update_flag(&self, flag: &FeatureFlag) -> Result<FeatureFlag>;
+    /// Removes the flag and every tenant override of it
+    async fn delete_flag(&self, key: &str) -> Result<()>;
+    async fn set_override(&self, tenant_override: &TenantFlagOverride) -> Result<TenantFlagOverride>;
+    async fn list_overrides(&self, tenant_id: &TenantId) -> Result<Vec<TenantFlagOverride>>;
+    async fn delete_override(&self, tenant_id: &TenantId, key: &str) -> Result<()>;
+}
+
+#[async_trait]
+pub trait TenantConfigRepository: Send + Sync {
+    async fn create_namespace(&self, namespace: &ConfigNamespace) -> Result<ConfigNamespace>;
+    async fn find_namespace(&self, name: &str) -> Result<Option<ConfigNamespace>>;
+    async fn list_namespaces(&self) -> Result<Vec<ConfigNamespace>>;
+    async fn update_namespace(&self, namespace: &ConfigNamespace) -> Result<ConfigNamespace>;
+    /// Removes the namespace and every tenant value stored in it
+    async fn delete_namespace(&self, name: &str) -> Result<()>;
+    async fn find_value(&self, tenant_id: &TenantId, namespace: &str) -> Result<Option<TenantConfigValue>>;
+    async fn list_values(&self, tenant_id: &TenantId) -> Result<Vec<TenantConfigValue>>;
+    async fn list_namespace_values(&self, namespace: &str) -> Result<Vec<TenantConfigValue>>;
+    async fn upsert_value(&self, value: &TenantConfigValue) -> Result<TenantConfigValue>;
+    async fn delete_value(&self, tenant_id: &TenantId, namespace: &str) -> Result<()>;
+}
+
+#[async_trait]
+pub trait TenantArchiveRepository: Send + Sync {
+    async fn upsert(&self, record: &TenantArchiveRecord) -> Result<TenantArchiveRecord>;
+    async fn find_by_tenant(&self, tenant_id: &TenantId) -> Result<Option<TenantArchiveRecord>>;
+    async fn list(&self) -> Result<Vec<TenantArchiveRecord>>;
+}
+
+#[async_trait]
+pub trait TenantDeletionRepository: Send + Sync {
+    async fn upsert(&self, record: &TenantDeletionRecord) -> Result<TenantDeletionRecord>;
+    async fn find_by_tenant(&self, tenant_id: &TenantId) -> Result<Option<TenantDeletionRecord>>;
+    async fn list(&self) -> Result<Vec<TenantDeletionRecord>>;
+}
+
+#[async_trait]
+pub trait LifecycleEventRepository: Send + Sync {
+    /// Appends an event to the log, assigning its sequence number
+    async fn append_event(&self, event: &TenantLifecycleEvent) -> Result<TenantLifecycleEvent>;
+    /// Events after the given sequence, oldest first
+    async fn list_events(&self, after_sequence: u64, tenant_id: Option<&TenantId>, limit: u32) -> Result<Vec<TenantLifecycleEvent>>;
+    async fn create_webhook(&self, webhook: &TenantWebhook) -> Result<TenantWebhook>;
+    async fn find_webhook(&self, id: &str) -> Result<Option<TenantWebhook>>;
+    async fn list_webhooks(&self) -> Result<Vec<TenantWebhook>>;
+    async fn update_webhook(&self, webhook: &TenantWebhook) -> Result<TenantWebhook>;
+    async fn delete_webhook(&self, id: &str) -> Result<()>;
+    async fn record_delivery(&self, delivery: &WebhookDelivery) -> Result<()>;
+    /// Most recent deliveries for a webhook, newest first
+    async fn list_deliveries(&self, webhook_id: &str, limit: u32) -> Result<Vec<WebhookDelivery>>;
+}
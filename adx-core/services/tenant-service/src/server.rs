@@ -17,9 +17,10 @@ use axum::{
 use sqlx::PgPool;
 // use std::time::Duration; // Commented out due to unused import
 
+use crate::grpc::{PermissionGrpcService, GRPC_PORT};
 use crate::handlers::*;
 use crate::services::TenantService;
-use crate::repositories_simple::{SimpleTenantRepository, SimpleTenantMembershipRepository};
+use crate::repositories::{PostgresTenantRepository, PostgresTenantMembershipRepository, PostgresTenantTemplateRepository, PostgresFeatureFlagRepository, PostgresTenantConfigRepository, PostgresTenantArchiveRepository, PostgresTenantDeletionRepository, PostgresLifecycleEventRepository};
 use adx_shared::{
     config::AppConfig,
     health::{health_check, HealthChecker, DatabaseHealthCheck},
@@ -93,14 +94,22 @@ async fn security_headers_middleware(
     response
 }
 
-pub async fn create_app(config: &AppConfig, pool: PgPool) -> Router {
-    // Create repositories (using simple in-memory implementation for now)
-    let tenant_repo = Arc::new(SimpleTenantRepository::new());
-    let membership_repo = Arc::new(SimpleTenantMembershipRepository::new());
+fn create_tenant_service(pool: PgPool) -> TenantServiceState {
+    // Create Postgres-backed repositories
+    let tenant_repo = Arc::new(PostgresTenantRepository::new(pool.clone()));
+    let membership_repo = Arc::new(PostgresTenantMembershipRepository::new(pool.clone()));
+    let template_repo = Arc::new(PostgresTenantTemplateRepository::new(pool.clone()));
+    let flag_repo = Arc::new(PostgresFeatureFlagRepository::new(pool.clone()));
+    let config_repo = Arc::new(PostgresTenantConfigRepository::new(pool.clone()));
+    let archive_repo = Arc::new(PostgresTenantArchiveRepository::new(pool.clone()));
+    let deletion_repo = Arc::new(PostgresTenantDeletionRepository::new(pool.clone()));
+    let event_repo = Arc::new(PostgresLifecycleEventRepository::new(pool.clone()));
 
     // Create service
-    let tenant_service = Arc::new(TenantService::new(tenant_repo, membership_repo));
+    Arc::new(TenantService::new(tenant_repo, membership_repo, template_repo, flag_repo, config_repo, archive_repo, deletion_repo, event_repo))
+}
 
+pub async fn create_app(config: &AppConfig, tenant_service: TenantServiceState) -> Router {
     // Health checker setup commented out for now
     // let mut health_checker = HealthChecker::new("tenant-service-2.0.0".to_string());
     // health_checker.add_check(DatabaseHealthCheck::new(pool.clone()));
@@ -117,6 +126,60 @@ pub async fn create_app(config: &AppConfig, pool: PgPool) -> Router {
         .route("/api/v1/tenants/:id", put(update_tenant))
         .route("/api/v1/tenants/:id", delete(delete_tenant))
         .route("/api/v1/tenants/slug/:slug", get(get_tenant_by_slug))
+        .route("/api/v1/tenants/:id/isolation", get(get_tenant_placement))
+        .route("/api/v1/tenants/:id/archive", get(get_tenant_archive))
+        .route("/api/v1/tenant-archives", get(list_tenant_archives))
+        .route("/api/v1/tenants/:id/deletion", get(get_tenant_deletion))
+        .route("/api/v1/tenants/:id/deletion/cancel", post(cancel_tenant_deletion))
+        .route("/api/v1/tenant-deletions", get(list_tenant_deletions))
+        .route("/api/v1/tenants/:id/trial/extend", post(extend_tenant_trial))
+        .route("/api/v1/tenants/:id/trial/convert", post(convert_tenant_trial))
+        
+        // Sub-tenant hierarchy routes
+        .route("/api/v1/tenants/:id/sub-tenants", post(create_sub_tenant))
+        .route("/api/v1/tenants/:id/sub-tenants", get(list_sub_tenants))
+        .route("/api/v1/tenants/:id/hierarchy", get(get_tenant_hierarchy))
+        .route("/api/v1/tenants/:id/detach", post(detach_sub_tenant))
+        
+        // Provisioning template routes
+        .route("/api/v1/tenant-templates", post(create_tenant_template))
+        .route("/api/v1/tenant-templates", get(list_tenant_templates))
+        .route("/api/v1/tenant-templates/:name", get(get_tenant_template))
+        .route("/api/v1/tenant-templates/:name", put(update_tenant_template))
+        .route("/api/v1/tenant-templates/:name", delete(delete_tenant_template))
+        
+        // Feature flag routes
+        .route("/api/v1/feature-flags", post(create_feature_flag))
+        .route("/api/v1/feature-flags", get(list_feature_flags))
+        .route("/api/v1/feature-flags/:key", get(get_feature_flag))
+        .route("/api/v1/feature-flags/:key", put(update_feature_flag))
+        .route("/api/v1/feature-flags/:key", delete(delete_feature_flag))
+        .route("/api/v1/tenants/:id/feature-flags", get(get_tenant_feature_flags))
+        .route("/api/v1/tenants/:id/feature-flags/evaluations", get(explain_tenant_feature_flags))
+        .route("/api/v1/tenants/:id/feature-flags/:key", put(set_tenant_flag_override))
+        .route("/api/v1/tenants/:id/feature-flags/:key", delete(delete_tenant_flag_override))
+        
+        // Tenant configuration routes
+        .route("/api/v1/config-namespaces", post(create_config_namespace))
+        .route("/api/v1/config-namespaces", get(list_config_namespaces))
+        .route("/api/v1/config-namespaces/:name", get(get_config_namespace))
+        .route("/api/v1/config-namespaces/:name", put(update_config_namespace))
+        .route("/api/v1/config-namespaces/:name", delete(delete_config_namespace))
+        .route("/api/v1/tenants/:id/config", get(list_tenant_config))
+        .route("/api/v1/tenants/:id/config/:namespace", get(get_tenant_config))
+        .route("/api/v1/tenants/:id/config/:namespace", put(set_tenant_config))
+        .route("/api/v1/tenants/:id/config/:namespace", delete(reset_tenant_config))
+        
+        // Lifecycle event stream and webhook routes (platform admin)
+        .route("/api/v1/admin/tenant-events", get(list_lifecycle_events))
+        .route("/api/v1/admin/tenant-events/stream", get(stream_lifecycle_events))
+        .route("/api/v1/admin/tenant-webhooks", post(create_webhook))
+        .route("/api/v1/admin/tenant-webhooks", get(list_webhooks))
+        .route("/api/v1/admin/tenant-webhooks/:id", get(get_webhook))
+        .route("/api/v1/admin/tenant-webhooks/:id", put(update_webhook))
+        .route("/api/v1/admin/tenant-webhooks/:id", delete(delete_webhook))
+        .route("/api/v1/admin/tenant-webhooks/:id/deliveries", get(list_webhook_deliveries))
+        .route("/api/v1/admin/tenant-webhooks/:id/rotate-secret", post(rotate_webhook_secret))
         
         // Tenant membership management routes
         .route("/api/v1/tenants/:tenant_id/members", post(create_membership))
@@ -142,7 +205,22 @@ pub async fn create_app(config: &AppConfig, pool: PgPool) -> Router {
 }
 
 pub async fn start_server(config: AppConfig, pool: PgPool) -> Result<(), Box<dyn std::error::Error>> {
-    let app = create_app(&config, pool).await;
+    let tenant_service = create_tenant_service(pool);
+    let app = create_app(&config, tenant_service.clone()).await;
+    
+    // Permission checks for the other services over gRPC
+    let grpc_addr = format!("{}:{}", config.server.host, GRPC_PORT).parse()?;
+    let grpc_service = PermissionGrpcService::new(tenant_service).into_server();
+    tokio::spawn(async move {
+        tracing::info!("🔌 Tenant Service gRPC server listening on {}", grpc_addr);
+        if let Err(e) = tonic::transport::Server::builder()
+            .add_service(grpc_service)
+            .serve(grpc_addr)
+            .await
+        {
+            tracing::error!("gRPC server error: {}", e);
+        }
+    });
     
     let port = 8085; // Fixed port for tenant service (dual-mode HTTP server)
     let addr = format!("{}:{}", config.server.host, port);
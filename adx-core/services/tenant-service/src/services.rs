@@ -1,24 +1,98 @@
+use std::collections::HashMap;
 use std::sync::Arc;
 use anyhow::{Result, anyhow};
 use chrono::Utc;
+use tokio::sync::{broadcast, RwLock};
 
 use crate::models::*;
-use crate::repository_traits::{TenantRepository, TenantMembershipRepository};
-use adx_shared::types::{TenantId, UserId};
+use crate::feature_flags::{
+    evaluate_tenant_flags, to_flag_set, validate_flag_key, validate_rollout_percentage, FlagEvaluation,
+};
+use crate::lifecycle_events::{
+    events_for_update, validate_webhook_url, TenantLifecycleEvent, TenantLifecycleEventType, WebhookDispatcher,
+    DEFAULT_SECRET_OVERLAP_HOURS, MAX_EVENT_PAGE_SIZE, MAX_SECRET_OVERLAP_HOURS,
+};
+use crate::repository_traits::{
+    FeatureFlagRepository, LifecycleEventRepository, TenantArchiveRepository, TenantConfigRepository, TenantDeletionRepository, TenantRepository, TenantMembershipRepository, TenantTemplateRepository,
+};
+use crate::hierarchy::{
+    effective_settings, grants_descendant_admin, rollup_quotas, validate_child_quotas,
+    TenantHierarchyNode, MAX_HIERARCHY_DEPTH,
+};
+use crate::tenant_config::{
+    merge_with_defaults, validate_config_value, validate_namespace_definition, validate_namespace_name,
+    ConfigChangeKind, TenantConfigChangeEvent,
+};
+use crate::templates::{default_template_name, resolve_provisioning_defaults, validate_template_name};
+use crate::trial::{trial_workflow_id, validate_extension_days, TrialSignal, TrialSignalRegistry};
+use adx_shared::context_token::{IssuedContextToken, TenantContextCache, TenantContextGrant, TenantContextSigner};
+use adx_shared::database::isolation::TenantPlacement;
+use adx_shared::feature_flags::FeatureFlagSet;
+use adx_shared::types::{SubscriptionTier, TenantId, UserId};
 
 pub struct TenantService {
     tenant_repo: Arc<dyn TenantRepository>,
     membership_repo: Arc<dyn TenantMembershipRepository>,
+    template_repo: Arc<dyn TenantTemplateRepository>,
+    flag_repo: Arc<dyn FeatureFlagRepository>,
+    config_repo: Arc<dyn TenantConfigRepository>,
+    archive_repo: Arc<dyn TenantArchiveRepository>,
+    deletion_repo: Arc<dyn TenantDeletionRepository>,
+    event_repo: Arc<dyn LifecycleEventRepository>,
+    trial_signals: Arc<TrialSignalRegistry>,
+    config_cache: RwLock<HashMap<(TenantId, String), ResolvedTenantConfig>>,
+    config_events: broadcast::Sender<TenantConfigChangeEvent>,
+    lifecycle_events: broadcast::Sender<TenantLifecycleEvent>,
+    webhooks: WebhookDispatcher,
+    context_signer: TenantContextSigner,
+    context_cache: Option<TenantContextCache>,
 }
 
+/// Buffered config change events per subscriber before it starts lagging
+const CONFIG_EVENT_CAPACITY: usize = 256;
+/// Buffered lifecycle events per admin stream subscriber
+const LIFECYCLE_EVENT_CAPACITY: usize = 1024;
+
 impl TenantService {
     pub fn new(
         tenant_repo: Arc<dyn TenantRepository>,
         membership_repo: Arc<dyn TenantMembershipRepository>,
+        template_repo: Arc<dyn TenantTemplateRepository>,
+        flag_repo: Arc<dyn FeatureFlagRepository>,
+        config_repo: Arc<dyn TenantConfigRepository>,
+        archive_repo: Arc<dyn TenantArchiveRepository>,
+        deletion_repo: Arc<dyn TenantDeletionRepository>,
+        event_repo: Arc<dyn LifecycleEventRepository>,
     ) -> Self {
+        let (config_events, _) = broadcast::channel(CONFIG_EVENT_CAPACITY);
+        let (lifecycle_events, _) = broadcast::channel(LIFECYCLE_EVENT_CAPACITY);
+
+        // Context tokens are signed with the platform secret the gateway verifies with
+        let context_secret = std::env::var("JWT_SECRET").unwrap_or_else(|_| "development-secret-key".to_string());
+        let context_cache = std::env::var("REDIS_URL").ok().and_then(|url| match TenantContextCache::new(&url) {
+            Ok(cache) => Some(cache),
+            Err(e) => {
+                tracing::warn!("Tenant context token cache disabled: {}", e);
+                None
+            }
+        });
+
         Self {
             tenant_repo,
             membership_repo,
+            template_repo,
+            flag_repo,
+            config_repo,
+            archive_repo,
+            deletion_repo,
+            event_repo: event_repo.clone(),
+            trial_signals: Arc::new(TrialSignalRegistry::new()),
+            config_cache: RwLock::new(HashMap::new()),
+            config_events,
+            lifecycle_events,
+            webhooks: WebhookDispatcher::new(event_repo),
+            context_signer: TenantContextSigner::new(&context_secret),
+            context_cache,
         }
     }
 
@@ -29,22 +103,42 @@ impl TenantService {
             return Err(anyhow!("Tenant with name '{}' already exists", request.name));
         }
 
+        let subscription_tier = request.subscription_tier.unwrap_or_default();
+        let template = self.resolve_template(request.template.as_deref(), &subscription_tier).await?;
+        let defaults = resolve_provisioning_defaults(
+            &template,
+            request.quotas,
+            &request.features.unwrap_or_default(),
+            &[],
+            request.settings,
+        );
+
         let tenant = Tenant {
             id: String::new(), // Will be generated in repository
             name: request.name,
             slug: String::new(), // Will be generated in repository
             admin_email: request.admin_email,
-            subscription_tier: request.subscription_tier.unwrap_or_default(),
+            subscription_tier,
             isolation_level: request.isolation_level.unwrap_or_default(),
-            quotas: Default::default(),
-            features: request.features.unwrap_or_default(),
-            settings: request.settings.unwrap_or_default(),
+            quotas: defaults.quotas,
+            features: defaults.features,
+            settings: defaults.settings,
             status: TenantStatus::Active,
+            parent_tenant_id: None,
+            inherit_settings: false,
+            region: None,
             created_at: Utc::now(),
             updated_at: Utc::now(),
         };
 
-        self.tenant_repo.create(&tenant).await
+        let tenant = self.tenant_repo.create(&tenant).await?;
+        self.publish_lifecycle_event(TenantLifecycleEvent::new(
+            TenantLifecycleEventType::Created,
+            &tenant,
+            serde_json::json!({ "template": template.name }),
+        )).await;
+
+        Ok(tenant)
     }
 
     pub async fn get_tenant(&self, id: &TenantId) -> Result<Option<Tenant>> {
@@ -62,6 +156,16 @@ impl TenantService {
     pub async fn update_tenant(&self, id: &TenantId, request: UpdateTenantRequest) -> Result<Tenant> {
         let mut tenant = self.tenant_repo.find_by_id(id).await?
             .ok_or_else(|| anyhow!("Tenant not found"))?;
+        let before = tenant.clone();
+
+        let changes_data = request.name.is_some()
+            || request.subscription_tier.is_some()
+            || request.quotas.is_some()
+            || request.features.is_some()
+            || request.settings.is_some();
+        if changes_data {
+            ensure_writable(&tenant)?;
+        }
 
         if let Some(name) = request.name {
             // Check if new name conflicts with existing tenant
@@ -93,26 +197,748 @@ impl TenantService {
             tenant.status = status;
         }
 
+        let tenant = self.tenant_repo.update(&tenant).await?;
+        for event in events_for_update(&before, &tenant) {
+            self.publish_lifecycle_event(event).await;
+        }
+
+        Ok(tenant)
+    }
+
+    /// Reset a tenant's features and quotas to a tier's template defaults and
+    /// reactivate it
+    pub async fn apply_tier_defaults(&self, id: &TenantId, tier: SubscriptionTier) -> Result<Tenant> {
+        let template = self.resolve_template(None, &tier).await?;
+
+        self.update_tenant(id, UpdateTenantRequest {
+            name: None,
+            subscription_tier: Some(tier),
+            quotas: Some(template.quotas),
+            features: Some(template.features),
+            settings: None,
+            status: Some(TenantStatus::Active),
+        }).await
+    }
+
+    pub async fn suspend_tenant(&self, id: &TenantId) -> Result<Tenant> {
+        self.update_tenant(id, UpdateTenantRequest {
+            name: None,
+            subscription_tier: None,
+            quotas: None,
+            features: None,
+            settings: None,
+            status: Some(TenantStatus::Suspended),
+        }).await
+    }
+
+    /// Put a tenant into read-only mode; only active tenants can be frozen
+    pub async fn freeze_tenant_writes(&self, id: &TenantId) -> Result<Tenant> {
+        let mut tenant = self.tenant_repo.find_by_id(id).await?
+            .ok_or_else(|| anyhow!("Tenant not found"))?;
+
+        if tenant.status != TenantStatus::Active {
+            return Err(anyhow!("Only active tenants can be frozen (status is {:?})", tenant.status));
+        }
+
+        tenant.status = TenantStatus::ReadOnly;
+        self.tenant_repo.update(&tenant).await
+    }
+
+    pub async fn unfreeze_tenant_writes(&self, id: &TenantId) -> Result<Tenant> {
+        let mut tenant = self.tenant_repo.find_by_id(id).await?
+            .ok_or_else(|| anyhow!("Tenant not found"))?;
+
+        if tenant.status == TenantStatus::ReadOnly {
+            tenant.status = TenantStatus::Active;
+            tenant = self.tenant_repo.update(&tenant).await?;
+        }
+
+        Ok(tenant)
+    }
+
+    pub async fn set_tenant_region(&self, id: &TenantId, region: &str) -> Result<Tenant> {
+        let mut tenant = self.tenant_repo.find_by_id(id).await?
+            .ok_or_else(|| anyhow!("Tenant not found"))?;
+
+        tenant.region = Some(region.to_string());
         self.tenant_repo.update(&tenant).await
     }
 
     pub async fn delete_tenant(&self, id: &TenantId) -> Result<()> {
-        // Check if tenant exists
-        if self.tenant_repo.find_by_id(id).await?.is_none() {
-            return Err(anyhow!("Tenant not found"));
+        let tenant = self.tenant_repo.find_by_id(id).await?
+            .ok_or_else(|| anyhow!("Tenant not found"))?;
+
+        if !self.tenant_repo.list_children(id).await?.is_empty() {
+            return Err(anyhow!("Tenant has sub-tenants; delete or detach them first"));
         }
 
         // TODO: In a real implementation, we would need to handle cascading deletes
         // and cleanup of tenant data, which should be done through a workflow
-        self.tenant_repo.delete(id).await
+        self.tenant_repo.delete(id).await?;
+
+        self.publish_lifecycle_event(TenantLifecycleEvent::new(
+            TenantLifecycleEventType::Terminated,
+            &tenant,
+            serde_json::json!({}),
+        )).await;
+
+        Ok(())
+    }
+
+    // Sub-tenant hierarchy operations
+    pub async fn create_sub_tenant(&self, parent_id: &TenantId, request: CreateSubTenantRequest) -> Result<Tenant> {
+        let parent = self.tenant_repo.find_by_id(parent_id).await?
+            .ok_or_else(|| anyhow!("Parent tenant not found"))?;
+
+        if parent.status != TenantStatus::Active {
+            return Err(anyhow!("Parent tenant is not active"));
+        }
+
+        // The new tenant sits one level below its parent
+        if self.get_ancestors(parent_id).await?.len() + 1 > MAX_HIERARCHY_DEPTH {
+            return Err(anyhow!("Tenant hierarchy cannot be deeper than {} levels", MAX_HIERARCHY_DEPTH));
+        }
+
+        if self.tenant_repo.find_by_name(&request.name).await?.is_some() {
+            return Err(anyhow!("Tenant with name '{}' already exists", request.name));
+        }
+
+        let template = self.resolve_template(request.template.as_deref(), &parent.subscription_tier).await?;
+        let defaults = resolve_provisioning_defaults(
+            &template,
+            request.quotas,
+            &request.features.unwrap_or_default(),
+            &[],
+            request.settings,
+        );
+
+        // Sub-tenants cannot unlock features their parent does not have
+        let features: Vec<String> = defaults.features.into_iter()
+            .filter(|feature| parent.features.contains(feature))
+            .collect();
+
+        let siblings: Vec<_> = self.tenant_repo.list_children(parent_id).await?
+            .into_iter()
+            .map(|t| t.quotas)
+            .collect();
+        validate_child_quotas(&parent.quotas, &siblings, &defaults.quotas)
+            .map_err(|errors| anyhow!("Sub-tenant quota exceeds parent allocation: {}", errors.join("; ")))?;
+
+        let tenant = Tenant {
+            id: String::new(), // Will be generated in repository
+            name: request.name,
+            slug: String::new(), // Will be generated in repository
+            admin_email: request.admin_email,
+            subscription_tier: parent.subscription_tier.clone(),
+            isolation_level: parent.isolation_level.clone(),
+            quotas: defaults.quotas,
+            features,
+            settings: defaults.settings,
+            status: TenantStatus::Active,
+            parent_tenant_id: Some(parent.id.clone()),
+            inherit_settings: request.inherit_settings,
+            region: parent.region.clone(),
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        let tenant = self.tenant_repo.create(&tenant).await?;
+        self.publish_lifecycle_event(TenantLifecycleEvent::new(
+            TenantLifecycleEventType::Created,
+            &tenant,
+            serde_json::json!({ "template": template.name, "parent_tenant_id": parent.id }),
+        )).await;
+
+        Ok(tenant)
+    }
+
+    pub async fn list_sub_tenants(&self, parent_id: &TenantId) -> Result<Vec<Tenant>> {
+        self.tenant_repo.list_children(parent_id).await
+    }
+
+    /// Ancestors of a tenant, nearest first
+    pub async fn get_ancestors(&self, id: &TenantId) -> Result<Vec<Tenant>> {
+        let mut ancestors = Vec::new();
+        let mut current = self.tenant_repo.find_by_id(id).await?
+            .ok_or_else(|| anyhow!("Tenant not found"))?;
+
+        while let Some(parent_id) = current.parent_tenant_id.clone() {
+            if ancestors.len() > MAX_HIERARCHY_DEPTH {
+                return Err(anyhow!("Tenant hierarchy for {} contains a cycle", id));
+            }
+
+            current = self.tenant_repo.find_by_id(&parent_id).await?
+                .ok_or_else(|| anyhow!("Parent tenant {} not found", parent_id))?;
+            ancestors.push(current.clone());
+        }
+
+        Ok(ancestors)
+    }
+
+    pub async fn get_effective_settings(&self, id: &TenantId) -> Result<TenantSettings> {
+        let tenant = self.tenant_repo.find_by_id(id).await?
+            .ok_or_else(|| anyhow!("Tenant not found"))?;
+        let ancestors = self.get_ancestors(id).await?;
+
+        Ok(effective_settings(&tenant, &ancestors))
+    }
+
+    /// Where the tenant's data lives, derived from the isolation level it was created with
+    pub async fn get_tenant_placement(&self, id: &TenantId) -> Result<TenantPlacement> {
+        let tenant = self.tenant_repo.find_by_id(id).await?
+            .ok_or_else(|| anyhow!("Tenant not found"))?;
+
+        Ok(TenantPlacement::new(&tenant.id, tenant.isolation_level)?)
+    }
+
+    pub async fn get_tenant_hierarchy(&self, id: &TenantId) -> Result<TenantHierarchyNode> {
+        let tenant = self.tenant_repo.find_by_id(id).await?
+            .ok_or_else(|| anyhow!("Tenant not found"))?;
+
+        self.build_hierarchy_node(tenant, 0).await
+    }
+
+    fn build_hierarchy_node<'a>(
+        &'a self,
+        tenant: Tenant,
+        depth: usize,
+    ) -> std::pin::Pin<Box<dyn std::future::Future<Output = Result<TenantHierarchyNode>> + Send + 'a>> {
+        Box::pin(async move {
+            if depth > MAX_HIERARCHY_DEPTH {
+                return Err(anyhow!("Tenant hierarchy for {} is deeper than {} levels", tenant.id, MAX_HIERARCHY_DEPTH));
+            }
+
+            let children = self.tenant_repo.list_children(&tenant.id).await?;
+            let allocated_to_children = rollup_quotas(
+                &children.iter().map(|child| child.quotas.clone()).collect::<Vec<_>>(),
+            );
+
+            let mut child_nodes = Vec::with_capacity(children.len());
+            for child in children {
+                child_nodes.push(self.build_hierarchy_node(child, depth + 1).await?);
+            }
+
+            Ok(TenantHierarchyNode {
+                tenant_id: tenant.id,
+                name: tenant.name,
+                slug: tenant.slug,
+                status: tenant.status,
+                inherit_settings: tenant.inherit_settings,
+                quotas: tenant.quotas,
+                allocated_to_children,
+                children: child_nodes,
+            })
+        })
+    }
+
+    /// Turn a sub-tenant into a top-level tenant. Inherited settings are copied
+    /// onto the tenant so it keeps behaving the same once the link is gone.
+    pub async fn detach_sub_tenant(&self, id: &TenantId, request: DetachSubTenantRequest) -> Result<Tenant> {
+        let mut tenant = self.tenant_repo.find_by_id(id).await?
+            .ok_or_else(|| anyhow!("Tenant not found"))?;
+
+        if tenant.parent_tenant_id.is_none() {
+            return Err(anyhow!("Tenant is not a sub-tenant"));
+        }
+
+        if tenant.inherit_settings {
+            tenant.settings = self.get_effective_settings(id).await?;
+            tenant.inherit_settings = false;
+        }
+
+        if let Some(subscription_tier) = request.subscription_tier {
+            tenant.subscription_tier = subscription_tier;
+        }
+
+        tenant.parent_tenant_id = None;
+
+        self.tenant_repo.update(&tenant).await
+    }
+
+    /// Whether the user holds an admin role on any ancestor of the tenant
+    async fn has_inherited_admin(&self, tenant_id: &TenantId, user_id: &UserId) -> Result<bool> {
+        let ancestors = match self.tenant_repo.find_by_id(tenant_id).await? {
+            Some(tenant) if tenant.parent_tenant_id.is_some() => self.get_ancestors(tenant_id).await?,
+            _ => return Ok(false),
+        };
+
+        for ancestor in ancestors {
+            if let Some(membership) = self.membership_repo.find_by_tenant_and_user(&ancestor.id, user_id).await? {
+                if membership.status == MembershipStatus::Active && grants_descendant_admin(&membership.role) {
+                    return Ok(true);
+                }
+            }
+        }
+
+        Ok(false)
+    }
+
+    // Provisioning template operations
+    pub async fn resolve_template(&self, name: Option<&str>, tier: &SubscriptionTier) -> Result<TenantTemplate> {
+        let name = name.unwrap_or_else(|| default_template_name(tier));
+        let template = self.template_repo.find_by_name(name).await?
+            .ok_or_else(|| anyhow!("Tenant template '{}' not found", name))?;
+
+        if !template.is_active {
+            return Err(anyhow!("Tenant template '{}' is not active", name));
+        }
+
+        Ok(template)
+    }
+
+    pub async fn create_template(&self, request: CreateTenantTemplateRequest) -> Result<TenantTemplate> {
+        validate_template_name(&request.name).map_err(|e| anyhow!(e))?;
+
+        if self.template_repo.find_by_name(&request.name).await?.is_some() {
+            return Err(anyhow!("Tenant template '{}' already exists", request.name));
+        }
+
+        let template = TenantTemplate {
+            name: request.name,
+            display_name: request.display_name,
+            description: request.description,
+            subscription_tier: request.subscription_tier,
+            quotas: request.quotas,
+            features: request.features,
+            default_roles: request.default_roles,
+            enabled_modules: request.enabled_modules,
+            sample_data: request.sample_data,
+            settings: request.settings,
+            is_builtin: false,
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        self.template_repo.create(&template).await
+    }
+
+    pub async fn get_template(&self, name: &str) -> Result<Option<TenantTemplate>> {
+        self.template_repo.find_by_name(name).await
+    }
+
+    pub async fn list_templates(&self, include_inactive: bool) -> Result<Vec<TenantTemplate>> {
+        self.template_repo.list(include_inactive).await
+    }
+
+    pub async fn update_template(&self, name: &str, request: UpdateTenantTemplateRequest) -> Result<TenantTemplate> {
+        let mut template = self.template_repo.find_by_name(name).await?
+            .ok_or_else(|| anyhow!("Tenant template not found"))?;
+
+        if let Some(display_name) = request.display_name {
+            template.display_name = display_name;
+        }
+
+        if let Some(description) = request.description {
+            template.description = Some(description);
+        }
+
+        if let Some(subscription_tier) = request.subscription_tier {
+            template.subscription_tier = subscription_tier;
+        }
+
+        if let Some(quotas) = request.quotas {
+            template.quotas = quotas;
+        }
+
+        if let Some(features) = request.features {
+            template.features = features;
+        }
+
+        if let Some(default_roles) = request.default_roles {
+            template.default_roles = default_roles;
+        }
+
+        if let Some(enabled_modules) = request.enabled_modules {
+            template.enabled_modules = enabled_modules;
+        }
+
+        if let Some(sample_data) = request.sample_data {
+            template.sample_data = sample_data;
+        }
+
+        if let Some(settings) = request.settings {
+            template.settings = Some(settings);
+        }
+
+        if let Some(is_active) = request.is_active {
+            template.is_active = is_active;
+        }
+
+        self.template_repo.update(&template).await
+    }
+
+    pub async fn delete_template(&self, name: &str) -> Result<()> {
+        let template = self.template_repo.find_by_name(name).await?
+            .ok_or_else(|| anyhow!("Tenant template not found"))?;
+
+        // Built-in templates back tenant creation for each tier; deactivate instead
+        if template.is_builtin {
+            return Err(anyhow!("Built-in template '{}' cannot be deleted", name));
+        }
+
+        self.template_repo.delete(name).await
+    }
+
+    // Feature flag operations
+    pub async fn create_feature_flag(&self, request: CreateFeatureFlagRequest) -> Result<FeatureFlag> {
+        validate_flag_key(&request.key).map_err(|e| anyhow!(e))?;
+        validate_rollout_percentage(request.rollout_percentage).map_err(|e| anyhow!(e))?;
+
+        if self.flag_repo.find_flag(&request.key).await?.is_some() {
+            return Err(anyhow!("Feature flag '{}' already exists", request.key));
+        }
+
+        let flag = FeatureFlag {
+            key: request.key,
+            description: request.description,
+            default_enabled: request.default_enabled,
+            enabled_tiers: request.enabled_tiers,
+            rollout_percentage: request.rollout_percentage,
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        self.flag_repo.create_flag(&flag).await
+    }
+
+    pub async fn get_feature_flag(&self, key: &str) -> Result<Option<FeatureFlag>> {
+        self.flag_repo.find_flag(key).await
+    }
+
+    pub async fn list_feature_flags(&self, include_inactive: bool) -> Result<Vec<FeatureFlag>> {
+        self.flag_repo.list_flags(include_inactive).await
+    }
+
+    pub async fn update_feature_flag(&self, key: &str, request: UpdateFeatureFlagRequest) -> Result<FeatureFlag> {
+        let mut flag = self.flag_repo.find_flag(key).await?
+            .ok_or_else(|| anyhow!("Feature flag not found"))?;
+
+        if let Some(description) = request.description {
+            flag.description = Some(description);
+        }
+
+        if let Some(default_enabled) = request.default_enabled {
+            flag.default_enabled = default_enabled;
+        }
+
+        if let Some(enabled_tiers) = request.enabled_tiers {
+            flag.enabled_tiers = enabled_tiers;
+        }
+
+        if let Some(rollout_percentage) = request.rollout_percentage {
+            validate_rollout_percentage(rollout_percentage).map_err(|e| anyhow!(e))?;
+            flag.rollout_percentage = rollout_percentage;
+        }
+
+        if let Some(is_active) = request.is_active {
+            flag.is_active = is_active;
+        }
+
+        self.flag_repo.update_flag(&flag).await
+    }
+
+    pub async fn delete_feature_flag(&self, key: &str) -> Result<()> {
+        self.flag_repo.find_flag(key).await?
+            .ok_or_else(|| anyhow!("Feature flag not found"))?;
+
+        self.flag_repo.delete_flag(key).await
+    }
+
+    pub async fn set_flag_override(
+        &self,
+        tenant_id: &TenantId,
+        key: &str,
+        request: SetFlagOverrideRequest,
+    ) -> Result<TenantFlagOverride> {
+        self.tenant_repo.find_by_id(tenant_id).await?
+            .ok_or_else(|| anyhow!("Tenant not found"))?;
+        self.flag_repo.find_flag(key).await?
+            .ok_or_else(|| anyhow!("Feature flag not found"))?;
+
+        self.flag_repo.set_override(&TenantFlagOverride {
+            tenant_id: tenant_id.clone(),
+            flag_key: key.to_string(),
+            enabled: request.enabled,
+            reason: request.reason,
+            updated_by: request.updated_by,
+            updated_at: Utc::now(),
+        }).await
+    }
+
+    pub async fn delete_flag_override(&self, tenant_id: &TenantId, key: &str) -> Result<()> {
+        self.flag_repo.delete_override(tenant_id, key).await
+    }
+
+    /// Every flag evaluated for a tenant, with the rule that decided each one
+    pub async fn explain_feature_flags(&self, tenant_id: &TenantId) -> Result<Vec<FlagEvaluation>> {
+        let tenant = self.tenant_repo.find_by_id(tenant_id).await?
+            .ok_or_else(|| anyhow!("Tenant not found"))?;
+        let flags = self.flag_repo.list_flags(true).await?;
+        let overrides = self.flag_repo.list_overrides(tenant_id).await?;
+
+        Ok(evaluate_tenant_flags(&flags, &tenant, &overrides))
+    }
+
+    pub async fn evaluate_feature_flags(&self, tenant_id: &TenantId) -> Result<FeatureFlagSet> {
+        let evaluations = self.explain_feature_flags(tenant_id).await?;
+        Ok(to_flag_set(tenant_id, &evaluations))
+    }
+
+    // Tenant configuration operations
+    pub async fn create_config_namespace(&self, request: CreateConfigNamespaceRequest) -> Result<ConfigNamespace> {
+        validate_namespace_name(&request.name).map_err(|e| anyhow!(e))?;
+        validate_namespace_definition(&request.schema, &request.default_value)
+            .map_err(|errors| anyhow!("Invalid namespace definition: {}", errors.join("; ")))?;
+
+        if self.config_repo.find_namespace(&request.name).await?.is_some() {
+            return Err(anyhow!("Config namespace '{}' already exists", request.name));
+        }
+
+        let namespace = ConfigNamespace {
+            name: request.name,
+            description: request.description,
+            owner_service: request.owner_service,
+            schema: request.schema,
+            default_value: request.default_value,
+            version: 1,
+            is_builtin: false,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        self.config_repo.create_namespace(&namespace).await
+    }
+
+    pub async fn get_config_namespace(&self, name: &str) -> Result<Option<ConfigNamespace>> {
+        self.config_repo.find_namespace(name).await
+    }
+
+    pub async fn list_config_namespaces(&self) -> Result<Vec<ConfigNamespace>> {
+        self.config_repo.list_namespaces().await
+    }
+
+    /// Change a namespace's schema or defaults. Rejected if any tenant's
+    /// current value would stop validating.
+    pub async fn update_config_namespace(
+        &self,
+        name: &str,
+        request: UpdateConfigNamespaceRequest,
+    ) -> Result<ConfigNamespace> {
+        let mut namespace = self.config_repo.find_namespace(name).await?
+            .ok_or_else(|| anyhow!("Config namespace not found"))?;
+
+        if let Some(description) = request.description {
+            namespace.description = Some(description);
+        }
+
+        let definition_changed = request.schema.is_some() || request.default_value.is_some();
+        if let Some(schema) = request.schema {
+            namespace.schema = schema;
+        }
+        if let Some(default_value) = request.default_value {
+            namespace.default_value = default_value;
+        }
+
+        if definition_changed {
+            validate_namespace_definition(&namespace.schema, &namespace.default_value)
+                .map_err(|errors| anyhow!("Invalid namespace definition: {}", errors.join("; ")))?;
+
+            for stored in self.config_repo.list_namespace_values(name).await? {
+                let merged = merge_with_defaults(&namespace.default_value, Some(&stored.value));
+                validate_config_value(&namespace.schema, &merged).map_err(|errors| {
+                    anyhow!(
+                        "Invalid namespace definition: tenant {} value would fail validation: {}",
+                        stored.tenant_id,
+                        errors.join("; ")
+                    )
+                })?;
+            }
+
+            namespace.version += 1;
+        }
+
+        let namespace = self.config_repo.update_namespace(&namespace).await?;
+        self.invalidate_config_namespace(name).await;
+        Ok(namespace)
+    }
+
+    pub async fn delete_config_namespace(&self, name: &str) -> Result<()> {
+        let namespace = self.config_repo.find_namespace(name).await?
+            .ok_or_else(|| anyhow!("Config namespace not found"))?;
+
+        // Built-in namespaces are read by other services on every request
+        if namespace.is_builtin {
+            return Err(anyhow!("Built-in config namespace '{}' cannot be deleted", name));
+        }
+
+        self.config_repo.delete_namespace(name).await?;
+        self.invalidate_config_namespace(name).await;
+        Ok(())
+    }
+
+    /// A tenant's settings in a namespace, merged over the namespace defaults
+    pub async fn get_tenant_config(&self, tenant_id: &TenantId, namespace: &str) -> Result<ResolvedTenantConfig> {
+        let cache_key = (tenant_id.clone(), namespace.to_string());
+        if let Some(cached) = self.config_cache.read().await.get(&cache_key) {
+            return Ok(cached.clone());
+        }
+
+        self.tenant_repo.find_by_id(tenant_id).await?
+            .ok_or_else(|| anyhow!("Tenant not found"))?;
+        let definition = self.config_repo.find_namespace(namespace).await?
+            .ok_or_else(|| anyhow!("Config namespace not found"))?;
+        let stored = self.config_repo.find_value(tenant_id, namespace).await?;
+
+        let resolved = ResolvedTenantConfig {
+            tenant_id: tenant_id.clone(),
+            namespace: namespace.to_string(),
+            value: merge_with_defaults(&definition.default_value, stored.as_ref().map(|s| &s.value)),
+            version: stored.as_ref().map(|s| s.version).unwrap_or(0),
+            schema_version: definition.version,
+            is_default: stored.is_none(),
+        };
+
+        self.config_cache.write().await.insert(cache_key, resolved.clone());
+        Ok(resolved)
+    }
+
+    /// Every namespace resolved for a tenant
+    pub async fn list_tenant_config(&self, tenant_id: &TenantId) -> Result<Vec<ResolvedTenantConfig>> {
+        let mut resolved = Vec::new();
+        for namespace in self.config_repo.list_namespaces().await? {
+            resolved.push(self.get_tenant_config(tenant_id, &namespace.name).await?);
+        }
+        Ok(resolved)
+    }
+
+    pub async fn set_tenant_config(
+        &self,
+        tenant_id: &TenantId,
+        namespace: &str,
+        request: SetTenantConfigRequest,
+    ) -> Result<ResolvedTenantConfig> {
+        let tenant = self.tenant_repo.find_by_id(tenant_id).await?
+            .ok_or_else(|| anyhow!("Tenant not found"))?;
+        ensure_writable(&tenant)?;
+        let definition = self.config_repo.find_namespace(namespace).await?
+            .ok_or_else(|| anyhow!("Config namespace not found"))?;
+
+        if !request.value.is_object() {
+            return Err(anyhow!("Invalid configuration for '{}': value must be an object", namespace));
+        }
+
+        let merged = merge_with_defaults(&definition.default_value, Some(&request.value));
+        validate_config_value(&definition.schema, &merged)
+            .map_err(|errors| anyhow!("Invalid configuration for '{}': {}", namespace, errors.join("; ")))?;
+
+        let previous = self.config_repo.find_value(tenant_id, namespace).await?;
+        let current_version = previous.as_ref().map(|p| p.version).unwrap_or(0);
+        if let Some(expected_version) = request.expected_version {
+            if expected_version != current_version {
+                return Err(anyhow!(
+                    "Configuration version conflict: expected {}, current is {}",
+                    expected_version,
+                    current_version
+                ));
+            }
+        }
+
+        let stored = self.config_repo.upsert_value(&TenantConfigValue {
+            tenant_id: tenant_id.clone(),
+            namespace: namespace.to_string(),
+            value: request.value,
+            version: current_version + 1,
+            updated_by: request.updated_by.clone(),
+            updated_at: Utc::now(),
+        }).await?;
+
+        self.publish_config_change(
+            tenant_id,
+            namespace,
+            ConfigChangeKind::Updated,
+            previous.map(|p| p.value),
+            Some(stored.value.clone()),
+            stored.version,
+            request.updated_by,
+        ).await;
+
+        self.get_tenant_config(tenant_id, namespace).await
+    }
+
+    /// Drop a tenant's stored settings so the namespace defaults apply again
+    pub async fn reset_tenant_config(&self, tenant_id: &TenantId, namespace: &str, changed_by: Option<UserId>) -> Result<()> {
+        let previous = self.config_repo.find_value(tenant_id, namespace).await?
+            .ok_or_else(|| anyhow!("Tenant configuration not found"))?;
+
+        self.config_repo.delete_value(tenant_id, namespace).await?;
+
+        self.publish_config_change(
+            tenant_id,
+            namespace,
+            ConfigChangeKind::Reset,
+            Some(previous.value),
+            None,
+            previous.version + 1,
+            changed_by,
+        ).await;
+
+        Ok(())
+    }
+
+    /// Change events for every tenant and namespace, for in-process consumers
+    pub fn subscribe_config_changes(&self) -> broadcast::Receiver<TenantConfigChangeEvent> {
+        self.config_events.subscribe()
+    }
+
+    #[allow(clippy::too_many_arguments)]
+    async fn publish_config_change(
+        &self,
+        tenant_id: &TenantId,
+        namespace: &str,
+        kind: ConfigChangeKind,
+        previous_value: Option<serde_json::Value>,
+        new_value: Option<serde_json::Value>,
+        version: u64,
+        changed_by: Option<UserId>,
+    ) {
+        self.config_cache.write().await.remove(&(tenant_id.clone(), namespace.to_string()));
+
+        let event = TenantConfigChangeEvent {
+            event_id: uuid::Uuid::new_v4().to_string(),
+            tenant_id: tenant_id.clone(),
+            namespace: namespace.to_string(),
+            kind,
+            previous_value,
+            new_value,
+            version,
+            changed_by,
+            changed_at: Utc::now(),
+        };
+
+        // No in-process subscribers is fine; the event still goes out to the owning service
+        let _ = self.config_events.send(event.clone());
+
+        tracing::info!(
+            "Would publish tenant.config.changed event {} for tenant {} namespace {} (version {})",
+            event.event_id,
+            tenant_id,
+            namespace,
+            version
+        );
+    }
+
+    async fn invalidate_config_namespace(&self, namespace: &str) {
+        self.config_cache.write().await.retain(|(_, cached_namespace), _| cached_namespace != namespace);
     }
 
     // Tenant membership operations
     pub async fn create_membership(&self, tenant_id: &TenantId, request: CreateMembershipRequest) -> Result<TenantMembership> {
-        // Verify tenant exists
-        if self.tenant_repo.find_by_id(tenant_id).await?.is_none() {
-            return Err(anyhow!("Tenant not found"));
-        }
+        // Verify tenant exists and accepts writes
+        let tenant = self.tenant_repo.find_by_id(tenant_id).await?
+            .ok_or_else(|| anyhow!("Tenant not found"))?;
+        ensure_writable(&tenant)?;
 
         // Check if membership already exists
         if let Some(_) = self.membership_repo.find_by_tenant_and_user(tenant_id, &request.user_id).await? {
@@ -164,16 +990,475 @@ impl TenantService {
             membership.status = status;
         }
 
-        self.membership_repo.update(&membership).await
+        let membership = self.membership_repo.update(&membership).await?;
+        self.invalidate_user_context_token(&membership.user_id, &membership.tenant_id).await;
+
+        Ok(membership)
     }
 
     pub async fn delete_membership(&self, id: &str) -> Result<()> {
-        // Check if membership exists
-        if self.membership_repo.find_by_id(id).await?.is_none() {
-            return Err(anyhow!("Membership not found"));
+        let membership = self.membership_repo.find_by_id(id).await?
+            .ok_or_else(|| anyhow!("Membership not found"))?;
+
+        self.membership_repo.delete(id).await?;
+        self.invalidate_user_context_token(&membership.user_id, &membership.tenant_id).await;
+
+        Ok(())
+    }
+
+    // Trial lifecycle operations
+
+    /// Drop an expired trial to free-tier features and quotas. The plan is kept
+    /// so an extension can restore it.
+    pub async fn downgrade_trial_features(&self, id: &TenantId) -> Result<Tenant> {
+        let free = self.resolve_template(None, &SubscriptionTier::Free).await?;
+
+        self.update_tenant(id, UpdateTenantRequest {
+            name: None,
+            subscription_tier: None,
+            quotas: Some(free.quotas),
+            features: Some(free.features),
+            settings: None,
+            status: None,
+        }).await
+    }
+
+    pub async fn open_trial_signals(&self, id: &TenantId) {
+        self.trial_signals.open(id).await;
+    }
+
+    pub async fn close_trial_signals(&self, id: &TenantId) {
+        self.trial_signals.close(id).await;
+    }
+
+    pub async fn wait_for_trial_signal(&self, id: &TenantId, timeout: std::time::Duration) -> Option<TrialSignal> {
+        self.trial_signals.wait(id, timeout).await
+    }
+
+    pub async fn extend_trial(&self, id: &TenantId, request: ExtendTrialRequest) -> Result<TrialSignalAccepted> {
+        validate_extension_days(request.days).map_err(|e| anyhow!(e))?;
+
+        self.signal_trial(id, TrialSignal::Extend {
+            days: request.days,
+            reason: request.reason,
+        }).await
+    }
+
+    pub async fn convert_trial(&self, id: &TenantId, request: ConvertTrialRequest) -> Result<TrialSignalAccepted> {
+        if request.subscription_tier == SubscriptionTier::Free {
+            return Err(anyhow!("Trials can only be converted to a paid plan"));
+        }
+
+        self.signal_trial(id, TrialSignal::Convert {
+            subscription_tier: request.subscription_tier,
+        }).await
+    }
+
+    async fn signal_trial(&self, id: &TenantId, signal: TrialSignal) -> Result<TrialSignalAccepted> {
+        self.tenant_repo.find_by_id(id).await?
+            .ok_or_else(|| anyhow!("Tenant not found"))?;
+
+        if !self.trial_signals.send(id, signal.clone()).await {
+            return Err(anyhow!("Running trial not found for tenant {}", id));
+        }
+
+        Ok(TrialSignalAccepted {
+            tenant_id: id.clone(),
+            workflow_id: trial_workflow_id(id),
+            signal,
+        })
+    }
+
+    // Archive and cold storage operations
+    pub async fn get_tenant_archive(&self, id: &TenantId) -> Result<Option<TenantArchiveRecord>> {
+        self.archive_repo.find_by_tenant(id).await
+    }
+
+    pub async fn list_tenant_archives(&self) -> Result<Vec<TenantArchiveRecord>> {
+        self.archive_repo.list().await
+    }
+
+    /// Record where an archived tenant's data went and mark the tenant archived
+    pub async fn mark_tenant_archived(&self, record: TenantArchiveRecord) -> Result<Tenant> {
+        let mut tenant = self.tenant_repo.find_by_id(&record.tenant_id).await?
+            .ok_or_else(|| anyhow!("Tenant not found"))?;
+
+        self.archive_repo.upsert(&record).await?;
+
+        tenant.status = TenantStatus::Archived;
+        let tenant = self.tenant_repo.update(&tenant).await?;
+
+        self.publish_lifecycle_event(TenantLifecycleEvent::new(
+            TenantLifecycleEventType::Archived,
+            &tenant,
+            serde_json::json!({ "archive_id": record.archive_id, "reason": record.reason }),
+        )).await;
+
+        Ok(tenant)
+    }
+
+    /// Remove an archived tenant's memberships; they are kept in the cold archive
+    pub async fn release_tenant_memberships(&self, id: &TenantId) -> Result<usize> {
+        let memberships = self.membership_repo.list_by_tenant(id).await?;
+        for membership in &memberships {
+            self.membership_repo.delete(&membership.id).await?;
+        }
+        Ok(memberships.len())
+    }
+
+    /// Start a restore and its SLA clock. Only one restore may run at a time.
+    pub async fn begin_tenant_restore(&self, id: &TenantId, requested_by: Option<UserId>) -> Result<TenantArchiveRecord> {
+        let tenant = self.tenant_repo.find_by_id(id).await?
+            .ok_or_else(|| anyhow!("Tenant not found"))?;
+        let mut record = self.archive_repo.find_by_tenant(id).await?
+            .ok_or_else(|| anyhow!("Tenant archive not found"))?;
+
+        if tenant.status != TenantStatus::Archived {
+            return Err(anyhow!("Tenant {} is not archived", id));
+        }
+
+        if matches!(&record.restore, Some(restore) if restore.completed_at.is_none()) {
+            return Err(anyhow!("A restore is already in progress for tenant {}", id));
+        }
+
+        record.restore = Some(TenantRestoreRecord::start(requested_by, &tenant.subscription_tier, Utc::now()));
+        self.archive_repo.upsert(&record).await
+    }
+
+    /// Put back memberships from a cold archive, keeping their original ids
+    pub async fn restore_archived_memberships(&self, id: &TenantId, memberships: &[TenantMembership]) -> Result<usize> {
+        for membership in memberships {
+            let mut restored = membership.clone();
+            restored.tenant_id = id.clone();
+            self.membership_repo.create(&restored).await?;
+        }
+        Ok(memberships.len())
+    }
+
+    /// Return a restored tenant to its pre-archive status and stop the SLA clock
+    pub async fn complete_tenant_restore(&self, id: &TenantId) -> Result<(Tenant, TenantArchiveRecord)> {
+        let mut tenant = self.tenant_repo.find_by_id(id).await?
+            .ok_or_else(|| anyhow!("Tenant not found"))?;
+        let mut record = self.archive_repo.find_by_tenant(id).await?
+            .ok_or_else(|| anyhow!("Tenant archive not found"))?;
+
+        let restore = record.restore.as_mut()
+            .ok_or_else(|| anyhow!("No restore in progress for tenant {}", id))?;
+        restore.complete(Utc::now());
+
+        if restore.sla_breached {
+            tracing::warn!("Restore of tenant {} missed its SLA deadline {}", id, restore.sla_deadline);
+        }
+
+        tenant.status = record.previous_status.clone();
+        let tenant = self.tenant_repo.update(&tenant).await?;
+        let record = self.archive_repo.upsert(&record).await?;
+
+        self.publish_lifecycle_event(TenantLifecycleEvent::new(
+            TenantLifecycleEventType::Restored,
+            &tenant,
+            serde_json::json!({ "archive_id": record.archive_id }),
+        )).await;
+
+        Ok((tenant, record))
+    }
+
+    // Staged deletion operations
+    pub async fn get_tenant_deletion(&self, id: &TenantId) -> Result<Option<TenantDeletionRecord>> {
+        self.deletion_repo.find_by_tenant(id).await
+    }
+
+    pub async fn list_tenant_deletions(&self) -> Result<Vec<TenantDeletionRecord>> {
+        self.deletion_repo.list().await
+    }
+
+    /// Take the tenant out of service and start its retention period. Nothing
+    /// is removed until the purge.
+    pub async fn soft_delete_tenant(
+        &self,
+        id: &TenantId,
+        requested_by: UserId,
+        reason: Option<String>,
+        retention_days: u32,
+    ) -> Result<TenantDeletionRecord> {
+        let mut tenant = self.tenant_repo.find_by_id(id).await?
+            .ok_or_else(|| anyhow!("Tenant not found"))?;
+
+        if let Some(existing) = self.deletion_repo.find_by_tenant(id).await? {
+            if matches!(existing.stage, TenantDeletionStage::SoftDeleted | TenantDeletionStage::Purging) {
+                return Err(anyhow!("Tenant {} is already scheduled for deletion", id));
+            }
+        }
+
+        let record = TenantDeletionRecord::start(&tenant, requested_by, reason, retention_days, Utc::now());
+
+        tenant.status = TenantStatus::PendingDeletion;
+        let tenant = self.tenant_repo.update(&tenant).await?;
+        let record = self.deletion_repo.upsert(&record).await?;
+
+        self.publish_lifecycle_event(TenantLifecycleEvent::new(
+            TenantLifecycleEventType::DeletionScheduled,
+            &tenant,
+            serde_json::json!({ "purge_after": record.purge_after, "reason": record.reason }),
+        )).await;
+
+        Ok(record)
+    }
+
+    /// Call off a deletion during its retention period and put the tenant back
+    /// in its previous status
+    pub async fn cancel_tenant_deletion(&self, id: &TenantId) -> Result<TenantDeletionRecord> {
+        let mut record = self.deletion_repo.find_by_tenant(id).await?
+            .ok_or_else(|| anyhow!("Tenant deletion not found"))?;
+
+        if !record.can_cancel() {
+            return Err(anyhow!("Deletion of tenant {} can no longer be cancelled ({:?})", id, record.stage));
+        }
+
+        let mut tenant = self.tenant_repo.find_by_id(id).await?
+            .ok_or_else(|| anyhow!("Tenant not found"))?;
+        tenant.status = record.previous_status.clone();
+        let tenant = self.tenant_repo.update(&tenant).await?;
+
+        record.stage = TenantDeletionStage::Cancelled;
+        record.cancelled_at = Some(Utc::now());
+        let record = self.deletion_repo.upsert(&record).await?;
+
+        self.publish_lifecycle_event(TenantLifecycleEvent::new(
+            TenantLifecycleEventType::DeletionCancelled,
+            &tenant,
+            serde_json::json!({}),
+        )).await;
+
+        Ok(record)
+    }
+
+    /// Move past the point of no return; cancellation is refused from here on
+    pub async fn begin_tenant_purge(&self, id: &TenantId) -> Result<TenantDeletionRecord> {
+        let mut record = self.deletion_repo.find_by_tenant(id).await?
+            .ok_or_else(|| anyhow!("Tenant deletion not found"))?;
+
+        match record.stage {
+            TenantDeletionStage::Purging => return Ok(record),
+            TenantDeletionStage::SoftDeleted if record.purge_due(Utc::now()) => {}
+            TenantDeletionStage::SoftDeleted => {
+                return Err(anyhow!("Tenant {} is in its retention period until {}", id, record.purge_after));
+            }
+            _ => return Err(anyhow!("Tenant {} is not awaiting a purge ({:?})", id, record.stage)),
+        }
+
+        record.stage = TenantDeletionStage::Purging;
+        self.deletion_repo.upsert(&record).await
+    }
+
+    pub async fn record_service_purge(&self, id: &TenantId, result: ServicePurgeResult) -> Result<TenantDeletionRecord> {
+        let mut record = self.deletion_repo.find_by_tenant(id).await?
+            .ok_or_else(|| anyhow!("Tenant deletion not found"))?;
+
+        record.purged_services.retain(|p| p.service != result.service);
+        record.purged_services.push(result);
+        self.deletion_repo.upsert(&record).await
+    }
+
+    /// Remove everything tenant-service holds for the tenant, ending with the
+    /// tenant row itself. Returns the number of records removed.
+    pub async fn purge_tenant_records(&self, id: &TenantId) -> Result<u64> {
+        let tenant = match self.tenant_repo.find_by_id(id).await? {
+            Some(tenant) => tenant,
+            // Already purged by an earlier attempt
+            None => return Ok(0),
+        };
+
+        let mut removed = self.release_tenant_memberships(id).await? as u64;
+
+        for value in self.config_repo.list_values(id).await? {
+            self.config_repo.delete_value(id, &value.namespace).await?;
+            removed += 1;
+        }
+        self.config_cache.write().await.retain(|(tenant_id, _), _| tenant_id != id);
+
+        for tenant_override in self.flag_repo.list_overrides(id).await? {
+            self.flag_repo.delete_override(id, &tenant_override.flag_key).await?;
+            removed += 1;
         }
 
-        self.membership_repo.delete(id).await
+        self.delete_tenant(&tenant.id).await?;
+        Ok(removed + 1)
+    }
+
+    /// Id for the tenant's destruction certificate, chosen once and reused on retries
+    pub async fn reserve_destruction_certificate(&self, id: &TenantId) -> Result<String> {
+        let mut record = self.deletion_repo.find_by_tenant(id).await?
+            .ok_or_else(|| anyhow!("Tenant deletion not found"))?;
+
+        if let Some(certificate_id) = &record.certificate_id {
+            return Ok(certificate_id.clone());
+        }
+
+        let certificate_id = uuid::Uuid::new_v4().to_string();
+        record.certificate_id = Some(certificate_id.clone());
+        self.deletion_repo.upsert(&record).await?;
+        Ok(certificate_id)
+    }
+
+    pub async fn complete_tenant_purge(&self, id: &TenantId, certificate_id: String) -> Result<TenantDeletionRecord> {
+        let mut record = self.deletion_repo.find_by_tenant(id).await?
+            .ok_or_else(|| anyhow!("Tenant deletion not found"))?;
+
+        record.stage = TenantDeletionStage::Purged;
+        record.purged_at.get_or_insert_with(Utc::now);
+        record.certificate_id = Some(certificate_id);
+        self.deletion_repo.upsert(&record).await
+    }
+
+    // Lifecycle events and webhooks
+    pub async fn list_lifecycle_events(
+        &self,
+        after_sequence: u64,
+        tenant_id: Option<&TenantId>,
+        limit: u32,
+    ) -> Result<Vec<TenantLifecycleEvent>> {
+        self.event_repo.list_events(after_sequence, tenant_id, limit.clamp(1, MAX_EVENT_PAGE_SIZE)).await
+    }
+
+    /// Live lifecycle events for the admin stream
+    pub fn subscribe_lifecycle_events(&self) -> broadcast::Receiver<TenantLifecycleEvent> {
+        self.lifecycle_events.subscribe()
+    }
+
+    /// Append an event to the log, then fan it out to stream subscribers and
+    /// webhooks. The tenant change has already been committed, so failures
+    /// here are logged rather than returned.
+    async fn publish_lifecycle_event(&self, event: TenantLifecycleEvent) {
+        let event = match self.event_repo.append_event(&event).await {
+            Ok(event) => event,
+            Err(e) => {
+                tracing::error!("Failed to record {} event for tenant {}: {}", event.event_type.as_str(), event.tenant_id, e);
+                return;
+            }
+        };
+
+        let _ = self.lifecycle_events.send(event.clone());
+
+        // Any lifecycle change can alter what a tenant's users may do
+        self.invalidate_context_tokens(&event.tenant_id).await;
+
+        match self.webhooks.dispatch(&event).await {
+            Ok(count) => tracing::info!(
+                "Published {} event {} for tenant {} to {} webhook(s)",
+                event.event_type.as_str(),
+                event.sequence,
+                event.tenant_id,
+                count
+            ),
+            Err(e) => tracing::error!("Failed to dispatch webhooks for event {}: {}", event.event_id, e),
+        }
+    }
+
+    pub async fn create_webhook(&self, request: CreateTenantWebhookRequest) -> Result<CreateTenantWebhookResponse> {
+        validate_webhook_url(&request.url).map_err(|e| anyhow!(e))?;
+
+        let secret = request.secret.unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string());
+        if secret.len() < 16 {
+            return Err(anyhow!("Webhook secret must be at least 16 characters"));
+        }
+
+        let webhook = TenantWebhook {
+            id: String::new(), // Will be generated in repository
+            url: request.url,
+            secret: secret.clone(),
+            previous_secret: None,
+            previous_secret_expires_at: None,
+            secret_rotated_at: None,
+            description: request.description,
+            event_types: request.event_types,
+            is_active: true,
+            created_at: Utc::now(),
+            updated_at: Utc::now(),
+        };
+
+        let webhook = self.event_repo.create_webhook(&webhook).await?;
+        Ok(CreateTenantWebhookResponse { webhook, secret })
+    }
+
+    /// Replace a webhook's signing secret. Deliveries keep a signature for the
+    /// old secret until the overlap window closes, so receivers can switch
+    /// without dropping events.
+    pub async fn rotate_webhook_secret(
+        &self,
+        id: &str,
+        request: RotateTenantWebhookSecretRequest,
+    ) -> Result<RotateTenantWebhookSecretResponse> {
+        let mut webhook = self.event_repo.find_webhook(id).await?
+            .ok_or_else(|| anyhow!("Webhook not found"))?;
+
+        let secret = request.secret.unwrap_or_else(|| uuid::Uuid::new_v4().simple().to_string());
+        if secret.len() < 16 {
+            return Err(anyhow!("Webhook secret must be at least 16 characters"));
+        }
+        if secret == webhook.secret {
+            // A retried rotation already applied; keep its overlap window
+            return Ok(RotateTenantWebhookSecretResponse { webhook, secret });
+        }
+
+        let overlap_hours = request.overlap_hours.unwrap_or(DEFAULT_SECRET_OVERLAP_HOURS);
+        if overlap_hours > MAX_SECRET_OVERLAP_HOURS {
+            return Err(anyhow!("Overlap cannot exceed {} hours", MAX_SECRET_OVERLAP_HOURS));
+        }
+
+        let now = Utc::now();
+        webhook.previous_secret = Some(std::mem::replace(&mut webhook.secret, secret.clone()));
+        webhook.previous_secret_expires_at = Some(now + chrono::Duration::hours(overlap_hours as i64));
+        webhook.secret_rotated_at = Some(now);
+
+        let webhook = self.event_repo.update_webhook(&webhook).await?;
+        Ok(RotateTenantWebhookSecretResponse { webhook, secret })
+    }
+
+    pub async fn get_webhook(&self, id: &str) -> Result<Option<TenantWebhook>> {
+        self.event_repo.find_webhook(id).await
+    }
+
+    pub async fn list_webhooks(&self) -> Result<Vec<TenantWebhook>> {
+        self.event_repo.list_webhooks().await
+    }
+
+    pub async fn update_webhook(&self, id: &str, request: UpdateTenantWebhookRequest) -> Result<TenantWebhook> {
+        let mut webhook = self.event_repo.find_webhook(id).await?
+            .ok_or_else(|| anyhow!("Webhook not found"))?;
+
+        if let Some(url) = request.url {
+            validate_webhook_url(&url).map_err(|e| anyhow!(e))?;
+            webhook.url = url;
+        }
+
+        if let Some(description) = request.description {
+            webhook.description = Some(description);
+        }
+
+        if let Some(event_types) = request.event_types {
+            webhook.event_types = event_types;
+        }
+
+        if let Some(is_active) = request.is_active {
+            webhook.is_active = is_active;
+        }
+
+        self.event_repo.update_webhook(&webhook).await
+    }
+
+    pub async fn delete_webhook(&self, id: &str) -> Result<()> {
+        if self.event_repo.find_webhook(id).await?.is_none() {
+            return Err(anyhow!("Webhook not found"));
+        }
+        self.event_repo.delete_webhook(id).await
+    }
+
+    pub async fn list_webhook_deliveries(&self, id: &str, limit: u32) -> Result<Vec<WebhookDelivery>> {
+        if self.event_repo.find_webhook(id).await?.is_none() {
+            return Err(anyhow!("Webhook not found"));
+        }
+        self.event_repo.list_deliveries(id, limit.clamp(1, MAX_EVENT_PAGE_SIZE)).await
     }
 
     // Tenant switching operations
@@ -194,31 +1479,83 @@ impl TenantService {
             .await?
             .ok_or_else(|| anyhow!("Target tenant not found"))?;
 
-        if tenant.status != TenantStatus::Active {
+        // Read-only tenants can still be browsed
+        if !matches!(tenant.status, TenantStatus::Active | TenantStatus::ReadOnly) {
             return Err(anyhow!("Target tenant is not active"));
         }
 
+        let settings = self.get_effective_settings(&tenant.id).await?;
+        let feature_flags = self.evaluate_feature_flags(&tenant.id).await?;
+
         // Build tenant context
         let tenant_context = TenantContext {
             tenant_id: tenant.id.clone(),
             tenant_name: tenant.name.clone(),
             tenant_slug: tenant.slug.clone(),
             subscription_tier: tenant.subscription_tier.clone(),
-            features: tenant.features.clone(),
+            feature_flags,
             quotas: tenant.quotas.clone(),
-            settings: tenant.settings.clone(),
+            settings,
             user_role: membership.role.clone(),
             user_permissions: membership.permissions.clone(),
         };
 
+        let session_id = uuid::Uuid::new_v4().to_string();
+        let context_token = self.issue_context_token(user_id, &session_id, &tenant_context).await?;
+
         Ok(SwitchTenantResponse {
             success: true,
             new_tenant_id: request.target_tenant_id,
-            new_session_id: None, // TODO: Generate new session ID
+            new_session_id: Some(session_id),
             tenant_context,
+            context_token,
         })
     }
 
+    /// Sign a context token for a session and cache it as the user's current
+    /// token for the tenant. A cache outage only costs the cached copy.
+    pub async fn issue_context_token(
+        &self,
+        user_id: &UserId,
+        session_id: &str,
+        context: &TenantContext,
+    ) -> Result<IssuedContextToken> {
+        let issued = self.context_signer.issue(TenantContextGrant {
+            user_id: user_id.clone(),
+            tenant_id: context.tenant_id.clone(),
+            tenant_name: context.tenant_name.clone(),
+            session_id: session_id.to_string(),
+            subscription_tier: context.subscription_tier.clone(),
+            role: format!("{:?}", context.user_role),
+            permissions: context.user_permissions.clone(),
+            features: context.feature_flags.enabled_keys(),
+        })?;
+
+        if let Some(cache) = &self.context_cache {
+            if let Err(e) = cache.put(user_id, &context.tenant_id, &issued).await {
+                tracing::warn!("Failed to cache context token for tenant {}: {}", context.tenant_id, e);
+            }
+        }
+
+        Ok(issued)
+    }
+
+    async fn invalidate_context_tokens(&self, tenant_id: &TenantId) {
+        if let Some(cache) = &self.context_cache {
+            if let Err(e) = cache.invalidate_tenant(tenant_id).await {
+                tracing::warn!("Failed to drop cached context tokens for tenant {}: {}", tenant_id, e);
+            }
+        }
+    }
+
+    async fn invalidate_user_context_token(&self, user_id: &UserId, tenant_id: &TenantId) {
+        if let Some(cache) = &self.context_cache {
+            if let Err(e) = cache.invalidate_user(user_id, tenant_id).await {
+                tracing::warn!("Failed to drop cached context token for user {} in tenant {}: {}", user_id, tenant_id, e);
+            }
+        }
+    }
+
     pub async fn get_tenant_context(&self, tenant_id: &TenantId, user_id: &UserId) -> Result<TenantContext> {
         // Get tenant information
         let tenant = self.tenant_repo
@@ -232,14 +1569,17 @@ impl TenantService {
             .await?
             .ok_or_else(|| anyhow!("User does not have access to tenant"))?;
 
+        let settings = self.get_effective_settings(&tenant.id).await?;
+        let feature_flags = self.evaluate_feature_flags(&tenant.id).await?;
+
         Ok(TenantContext {
             tenant_id: tenant.id,
             tenant_name: tenant.name,
             tenant_slug: tenant.slug,
             subscription_tier: tenant.subscription_tier,
-            features: tenant.features,
+            feature_flags,
             quotas: tenant.quotas,
-            settings: tenant.settings,
+            settings,
             user_role: membership.role,
             user_permissions: membership.permissions,
         })
@@ -249,7 +1589,7 @@ impl TenantService {
     pub async fn validate_tenant_access(&self, tenant_id: &TenantId, user_id: &UserId) -> Result<bool> {
         match self.membership_repo.find_by_tenant_and_user(tenant_id, user_id).await? {
             Some(membership) => Ok(membership.status == MembershipStatus::Active),
-            None => Ok(false),
+            None => self.has_inherited_admin(tenant_id, user_id).await,
         }
     }
 
@@ -282,7 +1622,23 @@ impl TenantService {
                     }
                 }
             }
-            None => Ok(false),
+            None => {
+                // Parent admins administer sub-tenants, but never as owner
+                if self.has_inherited_admin(tenant_id, user_id).await? {
+                    Ok(!permission.starts_with("owner:"))
+                } else {
+                    Ok(false)
+                }
+            }
         }
     }
-}
\ No newline at end of file
+}
+
+fn ensure_writable(tenant: &Tenant) -> Result<()> {
+    match tenant.status {
+        TenantStatus::ReadOnly => Err(anyhow!("Tenant {} is read-only", tenant.id)),
+        TenantStatus::Archived => Err(anyhow!("Tenant {} is archived; restore it first", tenant.id)),
+        TenantStatus::PendingDeletion => Err(anyhow!("Tenant {} is scheduled for deletion", tenant.id)),
+        _ => Ok(()),
+    }
+}
@@ -0,0 +1,275 @@
+use chrono::Utc;
+
+use crate::models::*;
+use adx_shared::types::{SubscriptionTier, TenantQuotas};
+
+// Built-in provisioning templates, one per subscription tier. Platform
+// operators can override these or add their own through the template API.
+
+pub const FREE_TEMPLATE: &str = "free";
+pub const PROFESSIONAL_TEMPLATE: &str = "professional";
+pub const ENTERPRISE_TEMPLATE: &str = "enterprise";
+pub const CUSTOM_TEMPLATE: &str = "custom";
+
+/// Name of the template applied when a tenant is created without one
+pub fn default_template_name(tier: &SubscriptionTier) -> &'static str {
+    match tier {
+        SubscriptionTier::Free => FREE_TEMPLATE,
+        SubscriptionTier::Professional => PROFESSIONAL_TEMPLATE,
+        SubscriptionTier::Enterprise => ENTERPRISE_TEMPLATE,
+        SubscriptionTier::Custom => CUSTOM_TEMPLATE,
+    }
+}
+
+fn strings(values: &[&str]) -> Vec<String> {
+    values.iter().map(|value| value.to_string()).collect()
+}
+
+fn default_roles(include_viewer: bool) -> Vec<TemplateRole> {
+    let mut roles = vec![
+        TemplateRole {
+            name: "admin".to_string(),
+            description: "Full administrative access to the tenant".to_string(),
+            permissions: strings(&["tenant:admin", "user:admin", "file:admin", "workflow:admin"]),
+            is_default: false,
+        },
+        TemplateRole {
+            name: "member".to_string(),
+            description: "Standard tenant member".to_string(),
+            permissions: strings(&["tenant:read", "user:read", "file:read", "file:write", "workflow:execute"]),
+            is_default: true,
+        },
+    ];
+
+    if include_viewer {
+        roles.push(TemplateRole {
+            name: "viewer".to_string(),
+            description: "Read-only access".to_string(),
+            permissions: strings(&["tenant:read", "user:read", "file:read"]),
+            is_default: false,
+        });
+    }
+
+    roles
+}
+
+fn builtin(
+    name: &str,
+    display_name: &str,
+    tier: SubscriptionTier,
+    quotas: TenantQuotas,
+    features: &[&str],
+    default_modules: &[&str],
+    sample_data: &[&str],
+) -> TenantTemplate {
+    let now = Utc::now();
+    TenantTemplate {
+        name: name.to_string(),
+        display_name: display_name.to_string(),
+        description: Some(format!("Default provisioning for {} tier tenants", display_name.to_lowercase())),
+        subscription_tier: tier.clone(),
+        quotas,
+        features: strings(features),
+        default_roles: default_roles(tier != SubscriptionTier::Free),
+        enabled_modules: strings(default_modules),
+        sample_data: strings(sample_data),
+        settings: None,
+        is_builtin: true,
+        is_active: true,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+/// Built-in templates seeded into every template repository
+pub fn builtin_templates() -> Vec<TenantTemplate> {
+    vec![
+        builtin(
+            FREE_TEMPLATE,
+            "Free",
+            SubscriptionTier::Free,
+            TenantQuotas {
+                max_users: Some(5),
+                max_storage_gb: Some(1),
+                max_api_calls_per_hour: Some(100),
+                max_workflows_per_hour: Some(10),
+            },
+            &["basic_auth", "file_storage"],
+            &[],
+            &["welcome_guide"],
+        ),
+        builtin(
+            PROFESSIONAL_TEMPLATE,
+            "Professional",
+            SubscriptionTier::Professional,
+            TenantQuotas {
+                max_users: Some(25),
+                max_storage_gb: Some(10),
+                max_api_calls_per_hour: Some(1000),
+                max_workflows_per_hour: Some(100),
+            },
+            &["basic_auth", "file_storage", "advanced_workflows", "api_access", "email_support"],
+            &["project-management"],
+            &["welcome_guide", "sample_projects"],
+        ),
+        builtin(
+            ENTERPRISE_TEMPLATE,
+            "Enterprise",
+            SubscriptionTier::Enterprise,
+            TenantQuotas {
+                max_users: Some(100),
+                max_storage_gb: Some(100),
+                max_api_calls_per_hour: Some(10000),
+                max_workflows_per_hour: Some(1000),
+            },
+            &[
+                "basic_auth", "file_storage", "advanced_workflows", "api_access", "email_support",
+                "sso_integration", "custom_branding", "priority_support", "audit_logs",
+            ],
+            &["project-management", "analytics"],
+            &[],
+        ),
+        builtin(
+            CUSTOM_TEMPLATE,
+            "Custom",
+            SubscriptionTier::Custom,
+            TenantQuotas {
+                max_users: None,
+                max_storage_gb: None,
+                max_api_calls_per_hour: None,
+                max_workflows_per_hour: None,
+            },
+            &["all_features"],
+            &[],
+            &[],
+        ),
+    ]
+}
+
+/// Template names are used in URLs and stored on tenants, so keep them simple
+pub fn validate_template_name(name: &str) -> Result<(), String> {
+    if name.len() < 2 || name.len() > 64 {
+        return Err("Template name must be between 2 and 64 characters".to_string());
+    }
+
+    if !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-' || c == '_') {
+        return Err("Template name may only contain lowercase letters, digits, '-' and '_'".to_string());
+    }
+
+    Ok(())
+}
+
+/// Values a template contributes to a new tenant after explicit request
+/// overrides have been applied
+#[derive(Debug, Clone)]
+pub struct ProvisioningDefaults {
+    pub template_name: String,
+    pub quotas: TenantQuotas,
+    pub features: Vec<String>,
+    pub default_roles: Vec<TemplateRole>,
+    pub enabled_modules: Vec<String>,
+    pub sample_data: Vec<String>,
+    pub settings: TenantSettings,
+}
+
+/// Resolve the effective provisioning values for a tenant. Explicit quotas and
+/// settings replace the template's; features and modules are merged.
+pub fn resolve_provisioning_defaults(
+    template: &TenantTemplate,
+    quotas: Option<TenantQuotas>,
+    features: &[String],
+    modules: &[String],
+    settings: Option<TenantSettings>,
+) -> ProvisioningDefaults {
+    let mut merged_features = template.features.clone();
+    for feature in features {
+        if !merged_features.contains(feature) {
+            merged_features.push(feature.clone());
+        }
+    }
+
+    let mut merged_modules = template.enabled_modules.clone();
+    for module in modules {
+        if !merged_modules.contains(module) {
+            merged_modules.push(module.clone());
+        }
+    }
+
+    ProvisioningDefaults {
+        template_name: template.name.clone(),
+        quotas: quotas.unwrap_or_else(|| template.quotas.clone()),
+        features: merged_features,
+        default_roles: template.default_roles.clone(),
+        enabled_modules: merged_modules,
+        sample_data: template.sample_data.clone(),
+        settings: settings.or_else(|| template.settings.clone()).unwrap_or_default(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn template(name: &str) -> TenantTemplate {
+        builtin_templates().into_iter().find(|t| t.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_every_tier_has_a_builtin_template() {
+        let templates = builtin_templates();
+        for tier in [
+            SubscriptionTier::Free,
+            SubscriptionTier::Professional,
+            SubscriptionTier::Enterprise,
+            SubscriptionTier::Custom,
+        ] {
+            let name = default_template_name(&tier);
+            let template = templates.iter().find(|t| t.name == name).unwrap();
+            assert_eq!(template.subscription_tier, tier);
+            assert!(template.is_builtin);
+        }
+    }
+
+    #[test]
+    fn test_template_name_validation() {
+        assert!(validate_template_name("startup-eu").is_ok());
+        assert!(validate_template_name("plan_2024").is_ok());
+        assert!(validate_template_name("x").is_err());
+        assert!(validate_template_name("Has Spaces").is_err());
+    }
+
+    #[test]
+    fn test_resolve_uses_template_values_by_default() {
+        let pro = template(PROFESSIONAL_TEMPLATE);
+        let defaults = resolve_provisioning_defaults(&pro, None, &[], &[], None);
+
+        assert_eq!(defaults.template_name, PROFESSIONAL_TEMPLATE);
+        assert_eq!(defaults.quotas.max_users, Some(25));
+        assert_eq!(defaults.features, pro.features);
+        assert_eq!(defaults.enabled_modules, vec!["project-management".to_string()]);
+        assert_eq!(defaults.default_roles.len(), 3);
+    }
+
+    #[test]
+    fn test_resolve_applies_overrides_and_merges_lists() {
+        let free = template(FREE_TEMPLATE);
+        let quotas = TenantQuotas {
+            max_users: Some(8),
+            max_storage_gb: Some(2),
+            max_api_calls_per_hour: Some(200),
+            max_workflows_per_hour: Some(20),
+        };
+        let defaults = resolve_provisioning_defaults(
+            &free,
+            Some(quotas),
+            &["file_storage".to_string(), "api_access".to_string()],
+            &["crm".to_string()],
+            None,
+        );
+
+        assert_eq!(defaults.quotas.max_users, Some(8));
+        assert_eq!(defaults.features, vec!["basic_auth", "file_storage", "api_access"]);
+        assert_eq!(defaults.enabled_modules, vec!["crm"]);
+        assert_eq!(defaults.default_roles.len(), 2);
+    }
+}
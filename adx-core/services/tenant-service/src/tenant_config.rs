@@ -0,0 +1,193 @@
+use chrono::{DateTime, Utc};
+use jsonschema::JSONSchema;
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
+
+use crate::models::*;
+use adx_shared::types::{TenantId, UserId};
+
+// Namespaced tenant configuration. Each namespace is owned by one service and
+// carries a JSON schema; tenant values are stored sparsely and merged over the
+// namespace defaults when read, and every stored value must satisfy the schema.
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum ConfigChangeKind {
+    Updated,
+    Reset,
+}
+
+/// Emitted whenever a tenant's value in a namespace changes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantConfigChangeEvent {
+    pub event_id: String,
+    pub tenant_id: TenantId,
+    pub namespace: String,
+    pub kind: ConfigChangeKind,
+    pub previous_value: Option<Value>,
+    pub new_value: Option<Value>,
+    pub version: u64,
+    pub changed_by: Option<UserId>,
+    pub changed_at: DateTime<Utc>,
+}
+
+pub fn validate_namespace_name(name: &str) -> Result<(), String> {
+    if name.len() < 2 || name.len() > 64 {
+        return Err("Namespace name must be between 2 and 64 characters".to_string());
+    }
+
+    if !name.chars().all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '.') {
+        return Err("Namespace name may only contain lowercase letters, digits, '_' and '.'".to_string());
+    }
+
+    Ok(())
+}
+
+/// Validate a value against a namespace schema, returning every violation
+pub fn validate_config_value(schema: &Value, value: &Value) -> Result<(), Vec<String>> {
+    let compiled = JSONSchema::compile(schema).map_err(|e| vec![format!("Invalid schema: {}", e)])?;
+
+    compiled.validate(value).map_err(|errors| {
+        errors
+            .map(|e| {
+                let path = e.instance_path.to_string();
+                if path.is_empty() {
+                    e.to_string()
+                } else {
+                    format!("{}: {}", path, e)
+                }
+            })
+            .collect::<Vec<String>>()
+    })
+}
+
+/// Check that a namespace's schema compiles, its values are objects and its
+/// defaults satisfy it
+pub fn validate_namespace_definition(schema: &Value, default_value: &Value) -> Result<(), Vec<String>> {
+    if schema.get("type").and_then(Value::as_str) != Some("object") {
+        return Err(vec!["Namespace schemas must describe an object".to_string()]);
+    }
+
+    validate_config_value(schema, default_value)
+}
+
+/// Overlay a tenant's stored keys on the namespace defaults. Merging is
+/// shallow: a stored top-level key replaces the default wholesale.
+pub fn merge_with_defaults(default_value: &Value, stored: Option<&Value>) -> Value {
+    match (default_value, stored) {
+        (Value::Object(defaults), Some(Value::Object(values))) => {
+            let mut merged = defaults.clone();
+            for (key, value) in values {
+                merged.insert(key.clone(), value.clone());
+            }
+            Value::Object(merged)
+        }
+        (_, Some(value)) => value.clone(),
+        (defaults, None) => defaults.clone(),
+    }
+}
+
+fn builtin(name: &str, owner_service: &str, description: &str, schema: Value, default_value: Value) -> ConfigNamespace {
+    let now = Utc::now();
+    ConfigNamespace {
+        name: name.to_string(),
+        description: Some(description.to_string()),
+        owner_service: owner_service.to_string(),
+        schema,
+        default_value,
+        version: 1,
+        is_builtin: true,
+        created_at: now,
+        updated_at: now,
+    }
+}
+
+/// Namespaces seeded into every config repository
+pub fn builtin_namespaces() -> Vec<ConfigNamespace> {
+    vec![
+        builtin(
+            "files",
+            "file-service",
+            "Upload limits and retention",
+            json!({
+                "type": "object",
+                "properties": {
+                    "max_upload_size_mb": { "type": "integer", "minimum": 1, "maximum": 5120 },
+                    "retention_days": { "type": ["integer", "null"], "minimum": 1 },
+                    "virus_scan": { "type": "boolean" }
+                },
+                "additionalProperties": false
+            }),
+            json!({ "max_upload_size_mb": 100, "retention_days": null, "virus_scan": true }),
+        ),
+        builtin(
+            "workflows",
+            "workflow-service",
+            "Workflow execution limits",
+            json!({
+                "type": "object",
+                "properties": {
+                    "default_timeout_secs": { "type": "integer", "minimum": 1 },
+                    "max_concurrent": { "type": "integer", "minimum": 1 },
+                    "history_retention_days": { "type": "integer", "minimum": 1, "maximum": 365 }
+                },
+                "additionalProperties": false
+            }),
+            json!({ "default_timeout_secs": 3600, "max_concurrent": 10, "history_retention_days": 30 }),
+        ),
+        builtin(
+            "localization",
+            "tenant-service",
+            "Default locale, timezone and formats",
+            json!({
+                "type": "object",
+                "properties": {
+                    "default_locale": { "type": "string", "pattern": "^[a-z]{2}(-[A-Z]{2})?$" },
+                    "timezone": { "type": "string", "minLength": 1 },
+                    "date_format": { "type": "string", "minLength": 1 }
+                },
+                "additionalProperties": false
+            }),
+            json!({ "default_locale": "en", "timezone": "UTC", "date_format": "YYYY-MM-DD" }),
+        ),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn namespace(name: &str) -> ConfigNamespace {
+        builtin_namespaces().into_iter().find(|n| n.name == name).unwrap()
+    }
+
+    #[test]
+    fn test_builtin_defaults_satisfy_their_schemas() {
+        for namespace in builtin_namespaces() {
+            assert!(validate_config_value(&namespace.schema, &namespace.default_value).is_ok(), "{}", namespace.name);
+            assert!(validate_namespace_name(&namespace.name).is_ok());
+        }
+    }
+
+    #[test]
+    fn test_values_are_validated_after_merging() {
+        let files = namespace("files");
+
+        let merged = merge_with_defaults(&files.default_value, Some(&json!({ "max_upload_size_mb": 250 })));
+        assert_eq!(merged["max_upload_size_mb"], 250);
+        assert_eq!(merged["virus_scan"], true);
+        assert!(validate_config_value(&files.schema, &merged).is_ok());
+
+        let errors = validate_config_value(&files.schema, &json!({ "max_upload_size_mb": 0, "colour": "red" })).unwrap_err();
+        assert_eq!(errors.len(), 2);
+        assert!(errors.iter().any(|e| e.starts_with("/max_upload_size_mb")));
+    }
+
+    #[test]
+    fn test_namespace_definition_checks() {
+        assert!(validate_namespace_definition(&json!({ "type": "string" }), &json!("x")).is_err());
+        assert!(validate_namespace_definition(&json!({ "type": "object", "required": ["a"] }), &json!({})).is_err());
+        assert!(validate_namespace_definition(&json!({ "type": "object" }), &json!({})).is_ok());
+        assert!(validate_namespace_name("Billing").is_err());
+    }
+}
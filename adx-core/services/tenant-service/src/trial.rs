@@ -0,0 +1,263 @@
+use chrono::{DateTime, Duration, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tokio::sync::{mpsc, Mutex, RwLock};
+
+use adx_shared::types::{SubscriptionTier, TenantId};
+
+// Trial lifecycle rules. A trial runs at its plan's defaults until it
+// expires, then drops to free-tier features for a grace period before the
+// tenant is suspended. Extensions and conversions arrive as signals to the
+// running trial workflow.
+
+pub const DEFAULT_TRIAL_DAYS: u32 = 14;
+pub const DEFAULT_GRACE_PERIOD_DAYS: u32 = 7;
+/// Days before expiry at which a reminder is sent
+pub const DEFAULT_REMINDER_DAYS: [u32; 3] = [7, 3, 1];
+/// Longest single extension an operator can grant
+pub const MAX_TRIAL_EXTENSION_DAYS: u32 = 30;
+
+pub fn trial_workflow_id(tenant_id: &str) -> String {
+    format!("tenant-trial-{}", tenant_id)
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "snake_case")]
+pub enum TrialPhase {
+    Active,
+    /// Past expiry; running on downgraded features until the grace period ends
+    Expired,
+    Suspended,
+    Converted,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum TrialSignal {
+    Extend { days: u32, reason: Option<String> },
+    Convert { subscription_tier: SubscriptionTier },
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TrialStep {
+    SendReminder { threshold_days: u32, days_remaining: i64 },
+    Expire,
+    Suspend,
+    WaitUntil(DateTime<Utc>),
+    Complete,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TrialSchedule {
+    pub started_at: DateTime<Utc>,
+    pub expires_at: DateTime<Utc>,
+    pub grace_period_days: u32,
+    /// Reminder thresholds in days before expiry, largest first
+    pub reminder_days: Vec<u32>,
+    pub reminders_sent: Vec<u32>,
+    pub extensions: u32,
+}
+
+impl TrialSchedule {
+    pub fn new(started_at: DateTime<Utc>, trial_days: u32, grace_period_days: u32, mut reminder_days: Vec<u32>) -> Self {
+        reminder_days.sort_unstable_by(|a, b| b.cmp(a));
+        reminder_days.dedup();
+
+        Self {
+            started_at,
+            expires_at: started_at + Duration::days(trial_days as i64),
+            grace_period_days,
+            reminder_days,
+            reminders_sent: Vec::new(),
+            extensions: 0,
+        }
+    }
+
+    pub fn grace_ends_at(&self) -> DateTime<Utc> {
+        self.expires_at + Duration::days(self.grace_period_days as i64)
+    }
+
+    /// Push expiry out. Extensions count from the later of the current expiry
+    /// and `now`, so extending a lapsed trial gives the full number of days.
+    pub fn extend(&mut self, days: u32, now: DateTime<Utc>) {
+        self.expires_at = self.expires_at.max(now) + Duration::days(days as i64);
+        self.extensions += 1;
+        // Reminders are re-armed against the new expiry
+        self.reminders_sent.clear();
+    }
+
+    /// Mark a reminder and every larger threshold as sent, so a late start
+    /// or an outage never produces a burst of stale reminders
+    pub fn record_reminder(&mut self, threshold_days: u32) {
+        for &days in &self.reminder_days {
+            if days >= threshold_days && !self.reminders_sent.contains(&days) {
+                self.reminders_sent.push(days);
+            }
+        }
+    }
+
+    /// What the workflow should do next for the given phase
+    pub fn next_step(&self, phase: &TrialPhase, now: DateTime<Utc>) -> TrialStep {
+        match phase {
+            TrialPhase::Active => {
+                if now >= self.expires_at {
+                    return TrialStep::Expire;
+                }
+
+                let remaining = self.expires_at - now;
+                // Whole days remaining, rounded up
+                let days_remaining = (remaining.num_seconds() + 86_399) / 86_400;
+
+                let due = self
+                    .reminder_days
+                    .iter()
+                    .filter(|&&days| days as i64 >= days_remaining && !self.reminders_sent.contains(&days))
+                    .min();
+                if let Some(&threshold_days) = due {
+                    return TrialStep::SendReminder { threshold_days, days_remaining };
+                }
+
+                let next_reminder = self
+                    .reminder_days
+                    .iter()
+                    .filter(|days| !self.reminders_sent.contains(days))
+                    .map(|&days| self.expires_at - Duration::days(days as i64))
+                    .filter(|at| *at > now)
+                    .min();
+
+                TrialStep::WaitUntil(next_reminder.map_or(self.expires_at, |at| at.min(self.expires_at)))
+            }
+            TrialPhase::Expired => {
+                if now >= self.grace_ends_at() {
+                    TrialStep::Suspend
+                } else {
+                    TrialStep::WaitUntil(self.grace_ends_at())
+                }
+            }
+            TrialPhase::Suspended | TrialPhase::Converted => TrialStep::Complete,
+        }
+    }
+}
+
+pub fn validate_extension_days(days: u32) -> Result<(), String> {
+    if days == 0 || days > MAX_TRIAL_EXTENSION_DAYS {
+        return Err(format!("Trial extensions must be between 1 and {} days", MAX_TRIAL_EXTENSION_DAYS));
+    }
+    Ok(())
+}
+
+struct TrialChannel {
+    sender: mpsc::UnboundedSender<TrialSignal>,
+    receiver: Arc<Mutex<mpsc::UnboundedReceiver<TrialSignal>>>,
+}
+
+/// Signal channels for running trial workflows, keyed by tenant. Stands in
+/// for Temporal workflow signals until the service runs on the Temporal SDK.
+#[derive(Default)]
+pub struct TrialSignalRegistry {
+    channels: RwLock<HashMap<TenantId, TrialChannel>>,
+}
+
+impl TrialSignalRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn open(&self, tenant_id: &str) {
+        let (sender, receiver) = mpsc::unbounded_channel();
+        self.channels.write().await.insert(
+            tenant_id.to_string(),
+            TrialChannel {
+                sender,
+                receiver: Arc::new(Mutex::new(receiver)),
+            },
+        );
+    }
+
+    pub async fn close(&self, tenant_id: &str) {
+        self.channels.write().await.remove(tenant_id);
+    }
+
+    pub async fn is_open(&self, tenant_id: &str) -> bool {
+        self.channels.read().await.contains_key(tenant_id)
+    }
+
+    /// Deliver a signal; returns false when no trial workflow is listening
+    pub async fn send(&self, tenant_id: &str, signal: TrialSignal) -> bool {
+        match self.channels.read().await.get(tenant_id) {
+            Some(channel) => channel.sender.send(signal).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Wait up to `timeout` for the next signal
+    pub async fn wait(&self, tenant_id: &str, timeout: std::time::Duration) -> Option<TrialSignal> {
+        let receiver = self.channels.read().await.get(tenant_id)?.receiver.clone();
+        let mut receiver = receiver.lock().await;
+
+        tokio::time::timeout(timeout, receiver.recv()).await.ok().flatten()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule() -> (TrialSchedule, DateTime<Utc>) {
+        let start = Utc::now();
+        (TrialSchedule::new(start, 14, 7, DEFAULT_REMINDER_DAYS.to_vec()), start)
+    }
+
+    #[test]
+    fn test_reminders_fire_once_per_threshold() {
+        let (mut schedule, start) = schedule();
+
+        assert_eq!(
+            schedule.next_step(&TrialPhase::Active, start),
+            TrialStep::WaitUntil(start + Duration::days(7))
+        );
+
+        let now = start + Duration::days(11) + Duration::hours(1);
+        assert_eq!(
+            schedule.next_step(&TrialPhase::Active, now),
+            TrialStep::SendReminder { threshold_days: 3, days_remaining: 3 }
+        );
+
+        // The missed 7-day reminder is skipped along with the one just sent
+        schedule.record_reminder(3);
+        assert_eq!(schedule.reminders_sent.len(), 2);
+        assert_eq!(
+            schedule.next_step(&TrialPhase::Active, now),
+            TrialStep::WaitUntil(start + Duration::days(13))
+        );
+    }
+
+    #[test]
+    fn test_expiry_then_grace_then_suspension() {
+        let (schedule, start) = schedule();
+
+        assert_eq!(schedule.next_step(&TrialPhase::Active, start + Duration::days(14)), TrialStep::Expire);
+        assert_eq!(
+            schedule.next_step(&TrialPhase::Expired, start + Duration::days(15)),
+            TrialStep::WaitUntil(start + Duration::days(21))
+        );
+        assert_eq!(schedule.next_step(&TrialPhase::Expired, start + Duration::days(21)), TrialStep::Suspend);
+        assert_eq!(schedule.next_step(&TrialPhase::Converted, start), TrialStep::Complete);
+    }
+
+    #[test]
+    fn test_extension_counts_from_now_when_lapsed() {
+        let (mut schedule, start) = schedule();
+        schedule.record_reminder(1);
+
+        let lapsed = start + Duration::days(16);
+        schedule.extend(10, lapsed);
+
+        assert_eq!(schedule.expires_at, lapsed + Duration::days(10));
+        assert_eq!(schedule.extensions, 1);
+        assert!(schedule.reminders_sent.is_empty());
+        assert!(validate_extension_days(0).is_err());
+        assert!(validate_extension_days(MAX_TRIAL_EXTENSION_DAYS + 1).is_err());
+    }
+}
@@ -3,8 +3,9 @@ use sqlx::PgPool;
 use anyhow::Result;
 
 use crate::services::TenantService;
-use crate::repositories_simple::{SimpleTenantRepository, SimpleTenantMembershipRepository};
+use crate::repositories::{PostgresTenantRepository, PostgresTenantMembershipRepository, PostgresTenantTemplateRepository, PostgresFeatureFlagRepository, PostgresTenantConfigRepository, PostgresTenantArchiveRepository, PostgresTenantDeletionRepository, PostgresLifecycleEventRepository};
 use crate::activities::{TenantActivities, TenantActivitiesImpl};
+use crate::deletion::HttpTenantPurgeClient;
 use crate::workflows::{TenantWorkflows, TenantWorkflowFactory};
 use adx_shared::config::AppConfig;
 
@@ -14,16 +15,22 @@ pub struct TenantWorker {
 }
 
 impl TenantWorker {
-    pub fn new(_config: &AppConfig, _pool: PgPool) -> Self {
-        // Create repositories (using simple in-memory implementation for now)
-        let tenant_repo = Arc::new(SimpleTenantRepository::new());
-        let membership_repo = Arc::new(SimpleTenantMembershipRepository::new());
+    pub fn new(_config: &AppConfig, pool: PgPool) -> Self {
+        // Create Postgres-backed repositories
+        let tenant_repo = Arc::new(PostgresTenantRepository::new(pool.clone()));
+        let membership_repo = Arc::new(PostgresTenantMembershipRepository::new(pool.clone()));
+        let template_repo = Arc::new(PostgresTenantTemplateRepository::new(pool.clone()));
+        let flag_repo = Arc::new(PostgresFeatureFlagRepository::new(pool.clone()));
+        let config_repo = Arc::new(PostgresTenantConfigRepository::new(pool.clone()));
+        let archive_repo = Arc::new(PostgresTenantArchiveRepository::new(pool.clone()));
+        let deletion_repo = Arc::new(PostgresTenantDeletionRepository::new(pool.clone()));
+        let event_repo = Arc::new(PostgresLifecycleEventRepository::new(pool.clone()));
 
         // Create service
-        let tenant_service = Arc::new(TenantService::new(tenant_repo, membership_repo));
+        let tenant_service = Arc::new(TenantService::new(tenant_repo, membership_repo, template_repo, flag_repo, config_repo, archive_repo, deletion_repo, event_repo));
 
         // Create activities
-        let activities = Arc::new(TenantActivitiesImpl::new(tenant_service));
+        let activities = Arc::new(TenantActivitiesImpl::new(tenant_service, Arc::new(HttpTenantPurgeClient::from_env())));
 
         // Create workflows
         let workflow_factory = TenantWorkflowFactory::new(activities.clone());
@@ -92,10 +99,81 @@ impl TenantWorker {
 
     pub async fn execute_terminate_tenant_workflow(
         &self,
-        tenant_id: adx_shared::types::TenantId,
-        export_data: bool,
-    ) -> Result<()> {
-        self.workflows.terminate_tenant_workflow(tenant_id, export_data).await
+        request: crate::models::TerminateTenantWorkflowRequest,
+    ) -> Result<crate::models::TerminateTenantWorkflowResult> {
+        self.workflows.terminate_tenant_workflow(request).await
+            .map_err(|e| anyhow::anyhow!("Workflow failed: {}", e))
+    }
+
+    pub async fn execute_clone_tenant_workflow(
+        &self,
+        request: crate::models::CloneTenantWorkflowRequest,
+    ) -> Result<crate::models::CloneTenantWorkflowResult> {
+        self.workflows.clone_tenant_workflow(request).await
+            .map_err(|e| anyhow::anyhow!("Workflow failed: {}", e))
+    }
+
+    pub async fn execute_create_sub_tenant_workflow(
+        &self,
+        request: crate::models::CreateSubTenantWorkflowRequest,
+    ) -> Result<crate::models::CreateSubTenantWorkflowResult> {
+        self.workflows.create_sub_tenant_workflow(request).await
+            .map_err(|e| anyhow::anyhow!("Workflow failed: {}", e))
+    }
+
+    pub async fn execute_detach_sub_tenant_workflow(
+        &self,
+        request: crate::models::DetachSubTenantWorkflowRequest,
+    ) -> Result<crate::models::DetachSubTenantWorkflowResult> {
+        self.workflows.detach_sub_tenant_workflow(request).await
+            .map_err(|e| anyhow::anyhow!("Workflow failed: {}", e))
+    }
+
+    pub async fn execute_tenant_trial_workflow(
+        &self,
+        request: crate::models::TenantTrialWorkflowRequest,
+    ) -> Result<crate::models::TenantTrialWorkflowResult> {
+        self.workflows.tenant_trial_workflow(request).await
+            .map_err(|e| anyhow::anyhow!("Workflow failed: {}", e))
+    }
+
+    pub async fn execute_archive_tenant_workflow(
+        &self,
+        request: crate::models::ArchiveTenantWorkflowRequest,
+    ) -> Result<crate::models::ArchiveTenantWorkflowResult> {
+        self.workflows.archive_tenant_workflow(request).await
+            .map_err(|e| anyhow::anyhow!("Workflow failed: {}", e))
+    }
+
+    pub async fn execute_restore_archived_tenant_workflow(
+        &self,
+        request: crate::models::RestoreArchivedTenantWorkflowRequest,
+    ) -> Result<crate::models::RestoreArchivedTenantWorkflowResult> {
+        self.workflows.restore_archived_tenant_workflow(request).await
+            .map_err(|e| anyhow::anyhow!("Workflow failed: {}", e))
+    }
+
+    pub async fn execute_migrate_tenant_region_workflow(
+        &self,
+        request: crate::models::MigrateTenantRegionWorkflowRequest,
+    ) -> Result<crate::models::MigrateTenantRegionWorkflowResult> {
+        self.workflows.migrate_tenant_region_workflow(request).await
+            .map_err(|e| anyhow::anyhow!("Workflow failed: {}", e))
+    }
+
+    pub async fn execute_export_tenant_workflow(
+        &self,
+        request: crate::models::ExportTenantWorkflowRequest,
+    ) -> Result<crate::models::ExportTenantWorkflowResult> {
+        self.workflows.export_tenant_workflow(request).await
+            .map_err(|e| anyhow::anyhow!("Workflow failed: {}", e))
+    }
+
+    pub async fn execute_import_tenant_workflow(
+        &self,
+        request: crate::models::ImportTenantWorkflowRequest,
+    ) -> Result<crate::models::ImportTenantWorkflowResult> {
+        self.workflows.import_tenant_workflow(request).await
             .map_err(|e| anyhow::anyhow!("Workflow failed: {}", e))
     }
 
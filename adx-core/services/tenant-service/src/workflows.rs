@@ -1,9 +1,19 @@
 use anyhow::Result;
 use std::sync::Arc;
+use chrono::Utc;
 
 use crate::activities::TenantActivities;
+use crate::deletion::{DEFAULT_DELETION_RETENTION_DAYS, DELETION_CHECK_INTERVAL_SECS};
 use crate::models::*;
-use adx_shared::types::TenantId;
+use crate::region_migration::{
+    validate_freeze_window, RegionMigrationProgress, RegionMigrationStep, DEFAULT_FREEZE_WINDOW_MINUTES,
+};
+use crate::templates::resolve_provisioning_defaults;
+use crate::trial::{
+    trial_workflow_id, TrialPhase, TrialSchedule, TrialSignal, TrialStep, DEFAULT_GRACE_PERIOD_DAYS,
+    DEFAULT_REMINDER_DAYS, DEFAULT_TRIAL_DAYS,
+};
+use adx_shared::types::{SubscriptionTier, TenantId};
 
 // Workflow error types
 #[derive(Debug, thiserror::Error)]
@@ -54,11 +64,31 @@ impl TenantWorkflows {
 
         let tenant_id = validation.tenant_id;
 
-        // Step 2: Set up tenant database schema/database
+        // Step 2: Resolve the provisioning template for the tenant's plan
+        let template = self.activities
+            .resolve_tenant_template(crate::activities::ResolveTenantTemplateRequest {
+                template: request.template.clone(),
+                subscription_tier: request.subscription_tier.clone(),
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "resolve_tenant_template".to_string(),
+                error: e.to_string(),
+            })?;
+
+        let defaults = resolve_provisioning_defaults(
+            &template,
+            request.quotas,
+            &request.features,
+            &request.default_modules,
+            None,
+        );
+
+        // Step 3: Set up tenant database schema/database
         let database_setup = self.activities
             .setup_tenant_database(crate::activities::SetupTenantDatabaseRequest {
                 tenant_id: tenant_id.clone(),
-                isolation_level: request.isolation_level,
+                isolation_level: request.isolation_level.clone(),
                 initial_schema: None,
             })
             .await
@@ -78,14 +108,17 @@ impl TenantWorkflows {
                 }
             })?;
 
-        // Step 3: Create tenant configuration
-        let _tenant_config = self.activities
+        // Step 4: Create tenant configuration from the template defaults
+        let tenant_config = self.activities
             .create_tenant_config(crate::activities::CreateTenantConfigRequest {
                 tenant_id: tenant_id.clone(),
                 tenant_name: request.tenant_name,
                 subscription_tier: request.subscription_tier,
-                quotas: request.quotas,
-                features: request.features,
+                isolation_level: request.isolation_level,
+                quotas: defaults.quotas,
+                features: defaults.features,
+                settings: Some(defaults.settings),
+                template: Some(defaults.template_name.clone()),
             })
             .await
             .map_err(|e| {
@@ -104,22 +137,86 @@ impl TenantWorkflows {
                 }
             })?;
 
-        // Step 4: Create admin user (this would typically call the auth service)
+        // Step 5: Create admin user (this would typically call the auth service)
         // For now, we'll simulate this step
         let admin_user_id = format!("admin-{}", uuid::Uuid::new_v4());
 
-        // Step 5: Install default modules (this would typically call the module service)
+        // Step 6: Create the template's default roles and assign admin
+        let role_definitions = defaults.default_roles
+            .iter()
+            .enumerate()
+            .map(|(level, role)| crate::activities::RoleDefinition {
+                name: role.name.clone(),
+                description: role.description.clone(),
+                permissions: role.permissions.clone(),
+                is_default: role.is_default,
+                hierarchy_level: level as u32,
+            })
+            .collect();
+
+        let permissions = self.activities
+            .setup_tenant_permissions_activity(crate::activities::SetupTenantPermissionsRequest {
+                tenant_id: tenant_id.clone(),
+                admin_user_id: admin_user_id.clone(),
+                role_definitions,
+                default_permissions: Vec::new(),
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "setup_tenant_permissions".to_string(),
+                error: e.to_string(),
+            })?;
+
+        // Step 7: Install template and requested modules (this would typically call the module service)
         // For now, we'll just log this step
-        for module_id in &request.default_modules {
+        for module_id in &defaults.enabled_modules {
             tracing::info!("Would install module {} for tenant {}", module_id, tenant_id);
         }
 
-        tracing::info!("Successfully created tenant: {}", tenant_id);
+        // Step 8: Seed sample data
+        let sample_data = self.activities
+            .seed_tenant_sample_data(crate::activities::SeedTenantSampleDataRequest {
+                tenant_id: tenant_id.clone(),
+                admin_user_id: admin_user_id.clone(),
+                datasets: defaults.sample_data,
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "seed_tenant_sample_data".to_string(),
+                error: e.to_string(),
+            })?;
+
+        // Step 9: Start the trial lifecycle for trial signups. It runs as an
+        // abandoned child workflow that outlives tenant creation.
+        let trial_workflow_id = request.trial_days.map(|trial_days| {
+            let workflows = TenantWorkflows::new(self.activities.clone());
+            let trial_request = TenantTrialWorkflowRequest {
+                tenant_id: tenant_config.id.clone(),
+                trial_days: Some(trial_days),
+                grace_period_days: None,
+                reminder_days: None,
+            };
+
+            tokio::spawn(async move {
+                if let Err(e) = workflows.tenant_trial_workflow(trial_request).await {
+                    tracing::error!("Trial workflow failed: {}", e);
+                }
+            });
+
+            trial_workflow_id(&tenant_config.id)
+        });
+
+        tracing::info!("Successfully created tenant: {} from template {}", tenant_id, defaults.template_name);
 
         Ok(CreateTenantWorkflowResult {
             tenant_id,
             admin_user_id,
             database_connection: database_setup.connection_string,
+            template: defaults.template_name,
+            roles_created: permissions.roles_created,
+            modules_installed: defaults.enabled_modules,
+            sample_data_seeded: sample_data.datasets_seeded,
+            trial_workflow_id,
         })
     }
 
@@ -190,7 +287,20 @@ impl TenantWorkflows {
                 error: e.to_string(),
             })?;
 
-        // Step 5: Update user's active tenant
+        // Step 5: Issue a signed context token so the gateway can skip tenant lookups
+        let context_token = self.activities
+            .issue_tenant_context_token(crate::activities::IssueTenantContextTokenRequest {
+                user_id: request.user_id.clone(),
+                session_id: new_session.session_id.clone(),
+                tenant_context: tenant_context.clone(),
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "issue_tenant_context_token".to_string(),
+                error: e.to_string(),
+            })?;
+
+        // Step 6: Update user's active tenant
         self.activities
             .update_user_active_tenant(crate::activities::UpdateUserActiveTenantRequest {
                 user_id: request.user_id,
@@ -209,6 +319,7 @@ impl TenantWorkflows {
             new_session_id: new_session.session_id,
             tenant_context,
             available_features: new_session.available_features,
+            context_token,
         })
     }
 
@@ -237,6 +348,945 @@ impl TenantWorkflows {
         Ok(())
     }
 
+    // Tenant clone workflow - copy a tenant into a sandbox/staging tenant
+    pub async fn clone_tenant_workflow(
+        &self,
+        request: CloneTenantWorkflowRequest,
+    ) -> Result<CloneTenantWorkflowResult, WorkflowError> {
+        tracing::info!("Starting clone tenant workflow from: {} to: {}",
+                      request.source_tenant_id, request.target_tenant_name);
+
+        // Step 1: Validate the source tenant and the requester's rights on it
+        let validation = self.activities
+            .validate_tenant_clone(crate::activities::ValidateTenantCloneRequest {
+                source_tenant_id: request.source_tenant_id.clone(),
+                target_tenant_name: request.target_tenant_name.clone(),
+                requested_by: request.requested_by.clone(),
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "validate_tenant_clone".to_string(),
+                error: e.to_string(),
+            })?;
+
+        if !validation.is_valid {
+            return Err(WorkflowError::ValidationFailed(validation.errors));
+        }
+
+        // Step 2: Collect the source tenant's configuration and selected data
+        let mut data = self.activities
+            .collect_tenant_export_data(crate::activities::CollectTenantExportDataRequest {
+                tenant_id: request.source_tenant_id.clone(),
+                include_users: request.include_members,
+                include_files: request.include_data,
+                include_workflow_history: false,
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "collect_tenant_export_data".to_string(),
+                error: e.to_string(),
+            })?;
+
+        // Step 3: Pseudonymize personal data with a salt unique to this clone
+        if request.anonymize {
+            let salt = uuid::Uuid::new_v4().to_string();
+            crate::cloning::anonymize_archive_data(&mut data, &salt);
+        }
+
+        // Step 4: Resolve roles and modules from the source tenant's plan template
+        let template = self.activities
+            .resolve_tenant_template(crate::activities::ResolveTenantTemplateRequest {
+                template: None,
+                subscription_tier: data.tenant.subscription_tier.clone(),
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "resolve_tenant_template".to_string(),
+                error: e.to_string(),
+            })?;
+
+        let defaults = resolve_provisioning_defaults(
+            &template,
+            Some(data.tenant.quotas.clone()),
+            &data.tenant.features,
+            &request.additional_modules,
+            None,
+        );
+
+        // Step 5: Create the clone with sandboxed configuration
+        let target_admin_email = request.target_admin_email
+            .unwrap_or_else(|| data.tenant.admin_email.clone());
+
+        let clone = self.activities
+            .create_tenant_clone(crate::activities::CreateTenantCloneRequest {
+                source: data.tenant.clone(),
+                target_tenant_name: request.target_tenant_name,
+                target_admin_email,
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "create_tenant_clone".to_string(),
+                error: e.to_string(),
+            })?;
+
+        // Step 6: Set up the clone's database
+        let database_setup = self.activities
+            .setup_tenant_database(crate::activities::SetupTenantDatabaseRequest {
+                tenant_id: clone.id.clone(),
+                isolation_level: clone.isolation_level.clone(),
+                initial_schema: None,
+            })
+            .await
+            .map_err(|e| {
+                let cleanup_tenant_id = clone.id.clone();
+                let activities = self.activities.clone();
+                tokio::spawn(async move {
+                    if let Err(cleanup_err) = activities.cleanup_tenant_database(&cleanup_tenant_id).await {
+                        tracing::error!("Failed to cleanup cloned tenant database: {}", cleanup_err);
+                    }
+                });
+
+                WorkflowError::ActivityFailed {
+                    activity: "setup_tenant_database".to_string(),
+                    error: e.to_string(),
+                }
+            })?;
+
+        // Step 7: Recreate roles, assigning admin to the requester
+        let role_definitions = defaults.default_roles
+            .iter()
+            .enumerate()
+            .map(|(level, role)| crate::activities::RoleDefinition {
+                name: role.name.clone(),
+                description: role.description.clone(),
+                permissions: role.permissions.clone(),
+                is_default: role.is_default,
+                hierarchy_level: level as u32,
+            })
+            .collect();
+
+        let permissions = self.activities
+            .setup_tenant_permissions_activity(crate::activities::SetupTenantPermissionsRequest {
+                tenant_id: clone.id.clone(),
+                admin_user_id: request.requested_by.clone(),
+                role_definitions,
+                default_permissions: Vec::new(),
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "setup_tenant_permissions".to_string(),
+                error: e.to_string(),
+            })?;
+
+        // Step 8: Install modules (this would typically call the module service)
+        for module_id in &defaults.enabled_modules {
+            tracing::info!("Would install module {} for cloned tenant {}", module_id, clone.id);
+        }
+
+        // Step 9: Copy memberships and data references
+        let copied = self.activities
+            .copy_tenant_data(crate::activities::CopyTenantDataRequest {
+                target_tenant_id: clone.id.clone(),
+                data,
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "copy_tenant_data".to_string(),
+                error: e.to_string(),
+            })?;
+
+        tracing::info!("Successfully cloned tenant: {} to: {}", request.source_tenant_id, clone.id);
+
+        Ok(CloneTenantWorkflowResult {
+            tenant_id: clone.id,
+            source_tenant_id: request.source_tenant_id,
+            database_connection: database_setup.connection_string,
+            roles_created: permissions.roles_created,
+            modules_installed: defaults.enabled_modules,
+            memberships_copied: copied.memberships_copied,
+            files_copied: copied.files_copied,
+            anonymized: request.anonymize,
+        })
+    }
+
+    // Sub-tenant creation workflow - carve a child tenant out of a parent's allocation
+    pub async fn create_sub_tenant_workflow(
+        &self,
+        request: CreateSubTenantWorkflowRequest,
+    ) -> Result<CreateSubTenantWorkflowResult, WorkflowError> {
+        tracing::info!("Starting create sub-tenant workflow for: {} under parent: {}",
+                      request.tenant_name, request.parent_tenant_id);
+
+        // Step 1: Validate the parent and the requester's admin rights on it
+        let validation = self.activities
+            .validate_sub_tenant_creation(crate::activities::ValidateSubTenantCreationRequest {
+                parent_tenant_id: request.parent_tenant_id.clone(),
+                tenant_name: request.tenant_name.clone(),
+                admin_email: request.admin_email.clone(),
+                requested_by: request.requested_by.clone(),
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "validate_sub_tenant_creation".to_string(),
+                error: e.to_string(),
+            })?;
+
+        if !validation.is_valid {
+            return Err(WorkflowError::ValidationFailed(validation.errors));
+        }
+
+        // Step 2: Create the sub-tenant record (checks quota allocation and depth)
+        let tenant = self.activities
+            .create_sub_tenant(crate::activities::CreateSubTenantActivityRequest {
+                parent_tenant_id: request.parent_tenant_id.clone(),
+                tenant_name: request.tenant_name,
+                admin_email: request.admin_email,
+                quotas: request.quotas,
+                features: request.features,
+                inherit_settings: request.inherit_settings,
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "create_sub_tenant".to_string(),
+                error: e.to_string(),
+            })?;
+
+        // Step 3: Set up the sub-tenant's database with the parent's isolation level
+        let database_setup = self.activities
+            .setup_tenant_database(crate::activities::SetupTenantDatabaseRequest {
+                tenant_id: tenant.id.clone(),
+                isolation_level: tenant.isolation_level.clone(),
+                initial_schema: None,
+            })
+            .await
+            .map_err(|e| {
+                let cleanup_tenant_id = tenant.id.clone();
+                let activities = self.activities.clone();
+                tokio::spawn(async move {
+                    if let Err(cleanup_err) = activities.cleanup_tenant_database(&cleanup_tenant_id).await {
+                        tracing::error!("Failed to cleanup sub-tenant database: {}", cleanup_err);
+                    }
+                });
+
+                WorkflowError::ActivityFailed {
+                    activity: "setup_tenant_database".to_string(),
+                    error: e.to_string(),
+                }
+            })?;
+
+        tracing::info!("Successfully created sub-tenant: {} under parent: {}", tenant.id, request.parent_tenant_id);
+
+        Ok(CreateSubTenantWorkflowResult {
+            tenant_id: tenant.id,
+            parent_tenant_id: request.parent_tenant_id,
+            quotas: tenant.quotas,
+            database_connection: database_setup.connection_string,
+        })
+    }
+
+    // Sub-tenant detach workflow - promote a sub-tenant to a top-level tenant
+    pub async fn detach_sub_tenant_workflow(
+        &self,
+        request: DetachSubTenantWorkflowRequest,
+    ) -> Result<DetachSubTenantWorkflowResult, WorkflowError> {
+        tracing::info!("Starting detach sub-tenant workflow for tenant: {}", request.tenant_id);
+
+        // Step 1: Detach, materializing inherited settings and releasing the parent's allocation
+        let detached = self.activities
+            .detach_sub_tenant(crate::activities::DetachSubTenantActivityRequest {
+                tenant_id: request.tenant_id.clone(),
+                requested_by: request.requested_by,
+                subscription_tier: request.subscription_tier,
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "detach_sub_tenant".to_string(),
+                error: e.to_string(),
+            })?;
+
+        tracing::info!("Successfully detached tenant: {} from parent: {}",
+                      request.tenant_id, detached.former_parent_tenant_id);
+
+        Ok(DetachSubTenantWorkflowResult {
+            tenant_id: request.tenant_id,
+            former_parent_tenant_id: detached.former_parent_tenant_id,
+            subscription_tier: detached.tenant.subscription_tier,
+            settings_materialized: detached.settings_materialized,
+        })
+    }
+
+    // Tenant export workflow - serialize tenant data into a versioned archive
+    pub async fn export_tenant_workflow(
+        &self,
+        request: ExportTenantWorkflowRequest,
+    ) -> Result<ExportTenantWorkflowResult, WorkflowError> {
+        tracing::info!("Starting tenant export workflow for tenant: {}", request.tenant_id);
+
+        // Step 1: Collect tenant data from the owning services
+        let data = self.activities
+            .collect_tenant_export_data(crate::activities::CollectTenantExportDataRequest {
+                tenant_id: request.tenant_id.clone(),
+                include_users: request.include_users,
+                include_files: request.include_files,
+                include_workflow_history: request.include_workflow_history,
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "collect_tenant_export_data".to_string(),
+                error: e.to_string(),
+            })?;
+
+        // Step 2: Write the archive with its manifest and checksum
+        let written = self.activities
+            .write_tenant_archive(crate::activities::WriteTenantArchiveRequest {
+                data,
+                source_environment: request.source_environment.unwrap_or_else(|| "unknown".to_string()),
+                exported_by: request.requested_by,
+                destination: request.destination
+                    .unwrap_or_else(|| crate::export::DEFAULT_EXPORT_DIR.to_string()),
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "write_tenant_archive".to_string(),
+                error: e.to_string(),
+            })?;
+
+        tracing::info!("Successfully exported tenant: {} to {}", request.tenant_id, written.location);
+
+        Ok(ExportTenantWorkflowResult {
+            tenant_id: request.tenant_id,
+            archive_id: written.manifest.archive_id,
+            archive_location: written.location,
+            format_version: written.manifest.format_version,
+            schema_version: written.manifest.schema_version,
+            checksum: written.manifest.checksum,
+            size_bytes: written.size_bytes,
+            users_exported: written.manifest.sections.users,
+            files_exported: written.manifest.sections.files,
+            workflow_references_exported: written.manifest.sections.workflow_history,
+        })
+    }
+
+    // Tenant import workflow - restore a tenant archive into this environment
+    pub async fn import_tenant_workflow(
+        &self,
+        request: ImportTenantWorkflowRequest,
+    ) -> Result<ImportTenantWorkflowResult, WorkflowError> {
+        tracing::info!("Starting tenant import workflow from: {}", request.archive_location);
+
+        // Step 1: Load the archive
+        let archive = self.activities
+            .load_tenant_archive(&request.archive_location)
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "load_tenant_archive".to_string(),
+                error: e.to_string(),
+            })?;
+
+        let target_tenant_name = request.target_tenant_name
+            .unwrap_or_else(|| archive.data.tenant.name.clone());
+
+        // Step 2: Check version compatibility, integrity and naming conflicts
+        let validation = self.activities
+            .validate_tenant_archive(crate::activities::ValidateTenantArchiveRequest {
+                manifest: archive.manifest.clone(),
+                data: archive.data.clone(),
+                target_tenant_name: target_tenant_name.clone(),
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "validate_tenant_archive".to_string(),
+                error: e.to_string(),
+            })?;
+
+        if !validation.is_valid {
+            return Err(WorkflowError::ValidationFailed(validation.errors));
+        }
+
+        for warning in &validation.warnings {
+            tracing::warn!("Tenant import warning for archive {}: {}", archive.manifest.archive_id, warning);
+        }
+
+        let archive_id = archive.manifest.archive_id.clone();
+        let source_tenant_id = archive.manifest.source_tenant_id.clone();
+        let source_environment = archive.manifest.source_environment.clone();
+
+        if request.dry_run {
+            tracing::info!("Dry run: archive {} can be imported as '{}'", archive_id, target_tenant_name);
+
+            return Ok(ImportTenantWorkflowResult {
+                tenant_id: None,
+                archive_id,
+                source_tenant_id,
+                source_environment,
+                dry_run: true,
+                memberships_restored: 0,
+                files_restored: 0,
+                workflow_references_restored: 0,
+                warnings: validation.warnings,
+            });
+        }
+
+        // Step 3: Restore tenant configuration, memberships and references
+        let target_admin_email = request.target_admin_email
+            .unwrap_or_else(|| archive.data.tenant.admin_email.clone());
+
+        let restored = self.activities
+            .restore_tenant_archive(crate::activities::RestoreTenantArchiveRequest {
+                archive,
+                target_tenant_name,
+                target_admin_email,
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "restore_tenant_archive".to_string(),
+                error: e.to_string(),
+            })?;
+
+        tracing::info!("Successfully imported archive {} as tenant: {}", archive_id, restored.tenant_id);
+
+        Ok(ImportTenantWorkflowResult {
+            tenant_id: Some(restored.tenant_id),
+            archive_id,
+            source_tenant_id,
+            source_environment,
+            dry_run: false,
+            memberships_restored: restored.memberships_restored,
+            files_restored: restored.files_restored,
+            workflow_references_restored: restored.workflow_references_restored,
+            warnings: validation.warnings,
+        })
+    }
+
+    // Tenant archive workflow - compress the tenant's data into cold storage and
+    // release its hot resources. The tenant row stays, marked archived.
+    pub async fn archive_tenant_workflow(
+        &self,
+        request: ArchiveTenantWorkflowRequest,
+    ) -> Result<ArchiveTenantWorkflowResult, WorkflowError> {
+        tracing::info!("Starting tenant archive workflow for tenant: {}", request.tenant_id);
+
+        // Step 1: Check the tenant can be archived and the requester may do it
+        let validation = self.activities
+            .validate_tenant_archival(crate::activities::ValidateTenantArchivalRequest {
+                tenant_id: request.tenant_id.clone(),
+                requested_by: request.requested_by.clone(),
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "validate_tenant_archival".to_string(),
+                error: e.to_string(),
+            })?;
+
+        if !validation.is_valid {
+            return Err(WorkflowError::ValidationFailed(validation.errors));
+        }
+
+        // Step 2: Collect everything needed to bring the tenant back
+        let data = self.activities
+            .collect_tenant_export_data(crate::activities::CollectTenantExportDataRequest {
+                tenant_id: request.tenant_id.clone(),
+                include_users: true,
+                include_files: true,
+                include_workflow_history: true,
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "collect_tenant_export_data".to_string(),
+                error: e.to_string(),
+            })?;
+
+        // Step 3: Write the compressed archive to cold storage
+        let record = self.activities
+            .offload_tenant_archive(crate::activities::OffloadTenantArchiveRequest {
+                data,
+                destination: request.destination
+                    .unwrap_or_else(|| crate::cold_storage::DEFAULT_COLD_STORAGE_DIR.to_string()),
+                archived_by: Some(request.requested_by.clone()),
+                reason: request.reason,
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "offload_tenant_archive".to_string(),
+                error: e.to_string(),
+            })?;
+
+        // Step 4: Mark the tenant archived before anything is released
+        self.activities
+            .mark_tenant_archived(record.clone())
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "mark_tenant_archived".to_string(),
+                error: e.to_string(),
+            })?;
+
+        // Step 5: Release hot database resources and memberships
+        let memberships_released = self.activities
+            .release_tenant_resources(&request.tenant_id)
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "release_tenant_resources".to_string(),
+                error: e.to_string(),
+            })?;
+
+        tracing::info!("Successfully archived tenant: {} to {}", request.tenant_id, record.location);
+
+        Ok(ArchiveTenantWorkflowResult {
+            tenant_id: request.tenant_id,
+            archive_id: record.archive_id,
+            location: record.location,
+            compressed_size_bytes: record.compressed_size_bytes,
+            uncompressed_size_bytes: record.uncompressed_size_bytes,
+            memberships_released,
+            archived_at: record.archived_at,
+        })
+    }
+
+    // Archived tenant restore workflow - bring a tenant back from cold storage,
+    // tracking the restore against its tier's SLA
+    pub async fn restore_archived_tenant_workflow(
+        &self,
+        request: RestoreArchivedTenantWorkflowRequest,
+    ) -> Result<RestoreArchivedTenantWorkflowResult, WorkflowError> {
+        tracing::info!("Starting archived tenant restore workflow for tenant: {}", request.tenant_id);
+
+        // Step 1: Start the restore and its SLA clock
+        let record = self.activities
+            .begin_tenant_restore(crate::activities::BeginTenantRestoreRequest {
+                tenant_id: request.tenant_id.clone(),
+                requested_by: request.requested_by.clone(),
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "begin_tenant_restore".to_string(),
+                error: e.to_string(),
+            })?;
+
+        // Step 2: Load and verify the cold archive
+        let archive = self.activities
+            .load_cold_tenant_archive(&record.location)
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "load_cold_tenant_archive".to_string(),
+                error: e.to_string(),
+            })?;
+
+        // Step 3: Re-provision the tenant's database
+        self.activities
+            .setup_tenant_database(crate::activities::SetupTenantDatabaseRequest {
+                tenant_id: request.tenant_id.clone(),
+                isolation_level: archive.data.tenant.isolation_level.clone(),
+                initial_schema: None,
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "setup_tenant_database".to_string(),
+                error: e.to_string(),
+            })?;
+
+        // Step 4: Restore memberships and data references
+        let memberships_restored = self.activities
+            .restore_archived_tenant_data(crate::activities::RestoreArchivedTenantDataRequest {
+                tenant_id: request.tenant_id.clone(),
+                archive,
+            })
+            .await
+            .map_err(|e| {
+                let cleanup_tenant_id = request.tenant_id.clone();
+                let activities = self.activities.clone();
+                tokio::spawn(async move {
+                    if let Err(cleanup_err) = activities.cleanup_tenant_database(&cleanup_tenant_id).await {
+                        tracing::error!("Failed to cleanup database for tenant {}: {}", cleanup_tenant_id, cleanup_err);
+                    }
+                });
+
+                WorkflowError::ActivityFailed {
+                    activity: "restore_archived_tenant_data".to_string(),
+                    error: e.to_string(),
+                }
+            })?;
+
+        // Step 5: Reopen the tenant and stop the SLA clock
+        let (tenant, record) = self.activities
+            .complete_tenant_restore(&request.tenant_id)
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "complete_tenant_restore".to_string(),
+                error: e.to_string(),
+            })?;
+
+        let restore = record.restore
+            .ok_or_else(|| WorkflowError::ExecutionFailed("Restore record missing after completion".to_string()))?;
+
+        tracing::info!("Successfully restored tenant: {} from archive {}", request.tenant_id, record.archive_id);
+
+        Ok(RestoreArchivedTenantWorkflowResult {
+            tenant_id: request.tenant_id,
+            archive_id: record.archive_id,
+            status: tenant.status,
+            memberships_restored,
+            sla_deadline: restore.sla_deadline,
+            completed_at: restore.completed_at.unwrap_or_else(Utc::now),
+            sla_breached: restore.sla_breached,
+        })
+    }
+
+    // Trial lifecycle workflow - long-running; sends expiry reminders, downgrades
+    // features at expiry and suspends after the grace period. Extend and
+    // convert requests arrive as signals.
+    pub async fn tenant_trial_workflow(
+        &self,
+        request: TenantTrialWorkflowRequest,
+    ) -> Result<TenantTrialWorkflowResult, WorkflowError> {
+        let tenant_id = request.tenant_id;
+        tracing::info!("Starting trial workflow for tenant: {}", tenant_id);
+
+        // Step 1: Open the signal channel and load the trial's plan
+        let tenant = self.activities
+            .begin_tenant_trial(&tenant_id)
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "begin_tenant_trial".to_string(),
+                error: e.to_string(),
+            })?;
+
+        let mut schedule = TrialSchedule::new(
+            Utc::now(),
+            request.trial_days.unwrap_or(DEFAULT_TRIAL_DAYS),
+            request.grace_period_days.unwrap_or(DEFAULT_GRACE_PERIOD_DAYS),
+            request.reminder_days.unwrap_or_else(|| DEFAULT_REMINDER_DAYS.to_vec()),
+        );
+        let mut subscription_tier = tenant.subscription_tier;
+
+        // Step 2: Drive the trial until it converts or is suspended
+        let outcome = self.run_trial_lifecycle(&tenant_id, &mut schedule, &mut subscription_tier).await;
+
+        // Step 3: Stop accepting signals, whether the trial finished or failed
+        if let Err(e) = self.activities.end_tenant_trial(&tenant_id).await {
+            tracing::error!("Failed to close trial signals for tenant {}: {}", tenant_id, e);
+        }
+
+        let outcome = outcome?;
+        tracing::info!("Trial workflow for tenant {} finished: {:?}", tenant_id, outcome);
+
+        Ok(TenantTrialWorkflowResult {
+            workflow_id: trial_workflow_id(&tenant_id),
+            tenant_id,
+            outcome,
+            subscription_tier,
+            expires_at: schedule.expires_at,
+            reminders_sent: schedule.reminders_sent,
+            extensions: schedule.extensions,
+        })
+    }
+
+    async fn run_trial_lifecycle(
+        &self,
+        tenant_id: &TenantId,
+        schedule: &mut TrialSchedule,
+        subscription_tier: &mut SubscriptionTier,
+    ) -> Result<TrialPhase, WorkflowError> {
+        let mut phase = TrialPhase::Active;
+
+        loop {
+            match schedule.next_step(&phase, Utc::now()) {
+                TrialStep::SendReminder { threshold_days, days_remaining } => {
+                    self.activities
+                        .send_trial_reminder(crate::activities::SendTrialReminderRequest {
+                            tenant_id: tenant_id.clone(),
+                            days_remaining,
+                            expires_at: schedule.expires_at,
+                        })
+                        .await
+                        .map_err(|e| WorkflowError::ActivityFailed {
+                            activity: "send_trial_reminder".to_string(),
+                            error: e.to_string(),
+                        })?;
+
+                    schedule.record_reminder(threshold_days);
+                }
+                TrialStep::Expire => {
+                    self.activities
+                        .downgrade_trial_features(tenant_id)
+                        .await
+                        .map_err(|e| WorkflowError::ActivityFailed {
+                            activity: "downgrade_trial_features".to_string(),
+                            error: e.to_string(),
+                        })?;
+
+                    phase = TrialPhase::Expired;
+                }
+                TrialStep::Suspend => {
+                    self.activities
+                        .suspend_trial_tenant(tenant_id)
+                        .await
+                        .map_err(|e| WorkflowError::ActivityFailed {
+                            activity: "suspend_trial_tenant".to_string(),
+                            error: e.to_string(),
+                        })?;
+
+                    return Ok(TrialPhase::Suspended);
+                }
+                TrialStep::Complete => return Ok(phase),
+                TrialStep::WaitUntil(until) => {
+                    // Sleep until the next timer fires or a signal arrives
+                    let timeout_secs = (until - Utc::now()).num_seconds().max(1) as u64;
+                    let signal = self.activities
+                        .wait_for_trial_signal(crate::activities::WaitForTrialSignalRequest {
+                            tenant_id: tenant_id.clone(),
+                            timeout_secs,
+                        })
+                        .await
+                        .map_err(|e| WorkflowError::ActivityFailed {
+                            activity: "wait_for_trial_signal".to_string(),
+                            error: e.to_string(),
+                        })?;
+
+                    match signal {
+                        Some(TrialSignal::Extend { days, reason }) => {
+                            tracing::info!("Extending trial for tenant {} by {} day(s): {:?}", tenant_id, days, reason);
+                            schedule.extend(days, Utc::now());
+
+                            if phase == TrialPhase::Expired {
+                                self.activities
+                                    .restore_trial_features(tenant_id)
+                                    .await
+                                    .map_err(|e| WorkflowError::ActivityFailed {
+                                        activity: "restore_trial_features".to_string(),
+                                        error: e.to_string(),
+                                    })?;
+
+                                phase = TrialPhase::Active;
+                            }
+                        }
+                        Some(TrialSignal::Convert { subscription_tier: target_tier }) => {
+                            let tenant = self.activities
+                                .convert_trial_tenant(crate::activities::ConvertTrialTenantRequest {
+                                    tenant_id: tenant_id.clone(),
+                                    subscription_tier: target_tier,
+                                })
+                                .await
+                                .map_err(|e| WorkflowError::ActivityFailed {
+                                    activity: "convert_trial_tenant".to_string(),
+                                    error: e.to_string(),
+                                })?;
+
+                            *subscription_tier = tenant.subscription_tier;
+                            return Ok(TrialPhase::Converted);
+                        }
+                        None => {}
+                    }
+                }
+            }
+        }
+    }
+
+    // Region migration workflow - move a tenant's data, storage and domains to
+    // another region. Data is copied while the tenant stays writable, then
+    // writes are frozen for the final sync and cutover. Any failure before
+    // writes reopen in the new region unwinds the completed steps.
+    pub async fn migrate_tenant_region_workflow(
+        &self,
+        request: MigrateTenantRegionWorkflowRequest,
+    ) -> Result<MigrateTenantRegionWorkflowResult, WorkflowError> {
+        tracing::info!("Starting region migration workflow for tenant: {} to region: {}",
+                      request.tenant_id, request.target_region);
+
+        let max_freeze_minutes = request.max_freeze_minutes.unwrap_or(DEFAULT_FREEZE_WINDOW_MINUTES);
+        validate_freeze_window(max_freeze_minutes)
+            .map_err(|e| WorkflowError::ValidationFailed(vec![e]))?;
+
+        // Step 1: Validate the target region, residency zone and requester
+        let validation = self.activities
+            .validate_region_migration(crate::activities::ValidateRegionMigrationRequest {
+                tenant_id: request.tenant_id.clone(),
+                target_region: request.target_region.clone(),
+                requested_by: request.requested_by.clone(),
+                required_jurisdiction: request.required_jurisdiction.clone(),
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "validate_region_migration".to_string(),
+                error: e.to_string(),
+            })?;
+
+        if !validation.is_valid {
+            return Err(WorkflowError::ValidationFailed(validation.errors));
+        }
+
+        let source_region = validation.source_region;
+        let mut progress = RegionMigrationProgress::default();
+
+        let outcome = self
+            .run_region_migration(&request, &source_region, max_freeze_minutes, &mut progress)
+            .await;
+
+        match outcome {
+            Ok(result) => {
+                tracing::info!("Successfully migrated tenant: {} from {} to {}",
+                              request.tenant_id, source_region, request.target_region);
+                Ok(result)
+            }
+            Err(e) => {
+                // Unwind in reverse order; keep going so a failed step does not strand the rest
+                for step in progress.compensations() {
+                    let compensation = self.activities
+                        .compensate_region_migration_step(crate::activities::CompensateRegionMigrationStepRequest {
+                            tenant_id: request.tenant_id.clone(),
+                            step,
+                            source_region: source_region.clone(),
+                            target_region: request.target_region.clone(),
+                        })
+                        .await;
+
+                    if let Err(compensation_err) = compensation {
+                        tracing::error!("Failed to compensate {:?} for tenant {}: {}",
+                                      step, request.tenant_id, compensation_err);
+                    }
+                }
+
+                Err(e)
+            }
+        }
+    }
+
+    async fn run_region_migration(
+        &self,
+        request: &MigrateTenantRegionWorkflowRequest,
+        source_region: &str,
+        max_freeze_minutes: u32,
+        progress: &mut RegionMigrationProgress,
+    ) -> Result<MigrateTenantRegionWorkflowResult, WorkflowError> {
+        let tenant_id = &request.tenant_id;
+        let target_region = &request.target_region;
+
+        // Step 2: Bulk replicate the database while the tenant stays writable
+        let replication = self.activities
+            .replicate_tenant_database(crate::activities::ReplicateTenantDatabaseRequest {
+                tenant_id: tenant_id.clone(),
+                source_region: source_region.to_string(),
+                target_region: target_region.clone(),
+                delta_only: false,
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "replicate_tenant_database".to_string(),
+                error: e.to_string(),
+            })?;
+        progress.record(RegionMigrationStep::ReplicateDatabase);
+
+        // Step 3: Copy storage objects
+        let storage = self.activities
+            .copy_tenant_storage(crate::activities::CopyTenantStorageRequest {
+                tenant_id: tenant_id.clone(),
+                source_region: source_region.to_string(),
+                target_region: target_region.clone(),
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "copy_tenant_storage".to_string(),
+                error: e.to_string(),
+            })?;
+        progress.record(RegionMigrationStep::CopyStorage);
+
+        // Step 4: Freeze writes; the freeze window starts here
+        self.activities
+            .freeze_tenant_writes(tenant_id)
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "freeze_tenant_writes".to_string(),
+                error: e.to_string(),
+            })?;
+        progress.record(RegionMigrationStep::FreezeWrites);
+        let frozen_at = std::time::Instant::now();
+
+        // Step 5: Sync changes made since the bulk copy
+        let delta = self.activities
+            .replicate_tenant_database(crate::activities::ReplicateTenantDatabaseRequest {
+                tenant_id: tenant_id.clone(),
+                source_region: source_region.to_string(),
+                target_region: target_region.clone(),
+                delta_only: true,
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "replicate_tenant_database".to_string(),
+                error: e.to_string(),
+            })?;
+        progress.record(RegionMigrationStep::SyncDelta);
+        let records_replicated = replication.records_replicated + delta.records_replicated;
+
+        // Step 6: Verify the replica against the frozen source
+        self.activities
+            .verify_region_replica(crate::activities::VerifyRegionReplicaRequest {
+                tenant_id: tenant_id.clone(),
+                target_region: target_region.clone(),
+                expected_records: records_replicated,
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "verify_region_replica".to_string(),
+                error: e.to_string(),
+            })?;
+        progress.record(RegionMigrationStep::VerifyReplica);
+
+        // Roll back rather than keep the tenant read-only past its window
+        if frozen_at.elapsed() > std::time::Duration::from_secs(max_freeze_minutes as u64 * 60) {
+            return Err(WorkflowError::ExecutionFailed(format!(
+                "Freeze window of {} minutes exceeded before cutover",
+                max_freeze_minutes
+            )));
+        }
+
+        // Step 7: Point the tenant's domains at the target region
+        let domains_cut_over = self.activities
+            .cutover_tenant_domains(crate::activities::CutoverTenantDomainsRequest {
+                tenant_id: tenant_id.clone(),
+                target_region: target_region.clone(),
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "cutover_tenant_domains".to_string(),
+                error: e.to_string(),
+            })?;
+        progress.record(RegionMigrationStep::CutoverDomains);
+
+        // Step 8: Record the new home region
+        self.activities
+            .switch_tenant_region(tenant_id, target_region)
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "switch_tenant_region".to_string(),
+                error: e.to_string(),
+            })?;
+        progress.record(RegionMigrationStep::SwitchRegion);
+
+        // Step 9: Reopen writes in the new region; the migration is committed
+        self.activities
+            .unfreeze_tenant_writes(tenant_id)
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "unfreeze_tenant_writes".to_string(),
+                error: e.to_string(),
+            })?;
+        progress.record(RegionMigrationStep::UnfreezeWrites);
+
+        // The source copy is kept until it ages out so support can recover from it
+        tracing::info!("Would schedule decommissioning of tenant {} data in {}", tenant_id, source_region);
+
+        Ok(MigrateTenantRegionWorkflowResult {
+            tenant_id: tenant_id.clone(),
+            source_region: source_region.to_string(),
+            target_region: target_region.clone(),
+            records_replicated,
+            objects_copied: storage.objects_copied,
+            bytes_copied: storage.bytes_copied,
+            domains_cut_over,
+            freeze_duration_ms: frozen_at.elapsed().as_millis() as u64,
+            completed_at: Utc::now(),
+        })
+    }
+
     // Tenant suspension workflow - suspend tenant for non-payment or violations
     pub async fn suspend_tenant_workflow(
         &self,
@@ -261,41 +1311,180 @@ impl TenantWorkflows {
         Ok(())
     }
 
-    // Tenant termination workflow - permanently delete tenant and all data
+    // Tenant termination workflow - staged deletion: soft-delete, wait out the
+    // retention period, purge every service, then get a signed destruction
+    // certificate from security-service
     pub async fn terminate_tenant_workflow(
         &self,
-        tenant_id: TenantId,
-        export_data: bool,
-    ) -> Result<(), WorkflowError> {
-        tracing::info!("Starting tenant termination workflow for tenant: {} (export_data: {})", 
-                      tenant_id, export_data);
+        request: TerminateTenantWorkflowRequest,
+    ) -> Result<TerminateTenantWorkflowResult, WorkflowError> {
+        let tenant_id = request.tenant_id.clone();
+        tracing::info!("Starting tenant termination workflow for tenant: {} (export_data: {})",
+                      tenant_id, request.export_data);
 
-        // This would implement the complex logic for terminating a tenant:
-        // 1. Validate termination request
-        // 2. Export tenant data if requested
-        // 3. Notify all tenant users
-        // 4. Delete all tenant data
-        // 5. Clean up database resources
-        // 6. Update billing status
+        // Step 1: Check the tenant can be deleted and the requester may do it
+        let validation = self.activities
+            .validate_tenant_deletion(crate::activities::ValidateTenantDeletionRequest {
+                tenant_id: tenant_id.clone(),
+                requested_by: request.requested_by.clone(),
+            })
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "validate_tenant_deletion".to_string(),
+                error: e.to_string(),
+            })?;
 
-        // For now, we'll just simulate the workflow
-        if export_data {
-            tracing::info!("Exporting tenant data before termination");
-            tokio::time::sleep(tokio::time::Duration::from_millis(200)).await;
+        if !validation.is_valid {
+            return Err(WorkflowError::ValidationFailed(validation.errors));
         }
 
-        // Clean up database
-        self.activities
-            .cleanup_tenant_database(&tenant_id)
+        // Step 2: Export tenant data while it is still intact, if requested
+        let export_location = if request.export_data {
+            let data = self.activities
+                .collect_tenant_export_data(crate::activities::CollectTenantExportDataRequest {
+                    tenant_id: tenant_id.clone(),
+                    include_users: true,
+                    include_files: true,
+                    include_workflow_history: true,
+                })
+                .await
+                .map_err(|e| WorkflowError::ActivityFailed {
+                    activity: "collect_tenant_export_data".to_string(),
+                    error: e.to_string(),
+                })?;
+
+            let written = self.activities
+                .write_tenant_archive(crate::activities::WriteTenantArchiveRequest {
+                    data,
+                    source_environment: "termination".to_string(),
+                    exported_by: Some(request.requested_by.clone()),
+                    destination: crate::export::DEFAULT_EXPORT_DIR.to_string(),
+                })
+                .await
+                .map_err(|e| WorkflowError::ActivityFailed {
+                    activity: "write_tenant_archive".to_string(),
+                    error: e.to_string(),
+                })?;
+
+            Some(written.location)
+        } else {
+            None
+        };
+
+        // Step 3: Soft-delete; the tenant stops serving but nothing is removed yet
+        let record = self.activities
+            .soft_delete_tenant(crate::activities::SoftDeleteTenantRequest {
+                tenant_id: tenant_id.clone(),
+                requested_by: request.requested_by,
+                reason: request.reason,
+                retention_days: request.retention_days.unwrap_or(DEFAULT_DELETION_RETENTION_DAYS),
+            })
             .await
             .map_err(|e| WorkflowError::ActivityFailed {
-                activity: "cleanup_tenant_database".to_string(),
+                activity: "soft_delete_tenant".to_string(),
                 error: e.to_string(),
             })?;
 
-        tracing::info!("Successfully terminated tenant: {}", tenant_id);
+        // Step 4: Wait out the retention period, stopping if the deletion is cancelled
+        let record = self.wait_for_deletion_retention(&tenant_id, record).await?;
+        if record.stage == TenantDeletionStage::Cancelled {
+            tracing::info!("Deletion of tenant {} was cancelled during retention", tenant_id);
+            return Ok(TerminateTenantWorkflowResult {
+                tenant_id,
+                stage: record.stage,
+                soft_deleted_at: record.soft_deleted_at,
+                export_location,
+                services_purged: Vec::new(),
+                purged_at: None,
+                certificate_id: None,
+            });
+        }
 
-        Ok(())
+        // Step 5: Past the point of no return; cancellation is refused from here
+        let record = self.activities
+            .begin_tenant_purge(&tenant_id)
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "begin_tenant_purge".to_string(),
+                error: e.to_string(),
+            })?;
+
+        // Step 6: Purge every service, skipping ones a previous attempt finished
+        for service in record.pending_purges() {
+            self.activities
+                .purge_tenant_service_data(crate::activities::PurgeTenantServiceDataRequest {
+                    tenant_id: tenant_id.clone(),
+                    service: service.to_string(),
+                })
+                .await
+                .map_err(|e| WorkflowError::ActivityFailed {
+                    activity: "purge_tenant_service_data".to_string(),
+                    error: format!("{}: {}", service, e),
+                })?;
+        }
+
+        // Step 7: Have security-service sign and store the destruction certificate
+        let record = self.activities
+            .get_tenant_deletion(&tenant_id)
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "get_tenant_deletion".to_string(),
+                error: e.to_string(),
+            })?;
+
+        let certificate_id = self.activities
+            .request_destruction_certificate(record)
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "request_destruction_certificate".to_string(),
+                error: e.to_string(),
+            })?;
+
+        // Step 8: Close out the deletion record
+        let record = self.activities
+            .complete_tenant_purge(&tenant_id, certificate_id)
+            .await
+            .map_err(|e| WorkflowError::ActivityFailed {
+                activity: "complete_tenant_purge".to_string(),
+                error: e.to_string(),
+            })?;
+
+        tracing::info!("Successfully terminated tenant: {} (certificate {:?})", tenant_id, record.certificate_id);
+
+        Ok(TerminateTenantWorkflowResult {
+            tenant_id,
+            stage: record.stage,
+            soft_deleted_at: record.soft_deleted_at,
+            export_location,
+            services_purged: record.purged_services,
+            purged_at: record.purged_at,
+            certificate_id: record.certificate_id,
+        })
+    }
+
+    async fn wait_for_deletion_retention(
+        &self,
+        tenant_id: &TenantId,
+        mut record: TenantDeletionRecord,
+    ) -> Result<TenantDeletionRecord, WorkflowError> {
+        // Durable timer in Temporal; here the wait wakes periodically to
+        // notice cancellations made through the API
+        while record.stage == TenantDeletionStage::SoftDeleted && !record.purge_due(Utc::now()) {
+            let remaining_secs = (record.purge_after - Utc::now()).num_seconds().max(1) as u64;
+            tokio::time::sleep(tokio::time::Duration::from_secs(
+                remaining_secs.min(DELETION_CHECK_INTERVAL_SECS),
+            )).await;
+
+            record = self.activities
+                .get_tenant_deletion(tenant_id)
+                .await
+                .map_err(|e| WorkflowError::ActivityFailed {
+                    activity: "get_tenant_deletion".to_string(),
+                    error: e.to_string(),
+                })?;
+        }
+
+        Ok(record)
     }
 
     // Tenant monitoring workflow - continuous resource tracking and alerts
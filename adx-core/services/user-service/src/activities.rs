@@ -5,8 +5,9 @@ use std::collections::HashMap;
 use uuid::Uuid;
 use chrono::{DateTime, Utc};
 use adx_shared::{
+    quotas::QuotaClient,
     temporal::{ActivityError, ActivityContext},
-    Result,
+    Result, ServiceError,
 };
 use crate::{
     models::*,
@@ -232,6 +233,7 @@ pub struct UserServiceActivitiesImpl {
     preference_repo: Arc<dyn UserPreferenceRepository>,
     activity_repo: Arc<dyn UserActivityRepository>,
     validator: Arc<UserValidator>,
+    quota_client: Arc<QuotaClient>,
 }
 
 impl UserServiceActivitiesImpl {
@@ -241,6 +243,7 @@ impl UserServiceActivitiesImpl {
         preference_repo: Arc<dyn UserPreferenceRepository>,
         activity_repo: Arc<dyn UserActivityRepository>,
         validator: Arc<UserValidator>,
+        quota_client: Arc<QuotaClient>,
     ) -> Self {
         Self {
             user_repo,
@@ -248,6 +251,7 @@ impl UserServiceActivitiesImpl {
             preference_repo,
             activity_repo,
             validator,
+            quota_client,
         }
     }
 }
@@ -268,9 +272,28 @@ impl UserServiceActivities for UserServiceActivitiesImpl {
             return Err(adx_shared::Error::Conflict("User with this email already exists".to_string()));
         }
         
+        // Check the tenant has a seat for the user
+        let tenant_id = request.tenant_id.to_string();
+        match self.quota_client.require_seat(&tenant_id).await {
+            Err(ServiceError::QuotaExceeded(message)) => return Err(ServiceError::QuotaExceeded(message)),
+            Err(e) => tracing::warn!("Could not check seats of tenant {}: {}", tenant_id, e),
+            Ok(_) => {}
+        }
+        
         // Create the user
         let user = self.user_repo.create(request.tenant_id, request.user_request.clone()).await?;
         
+        // Take the seat; losing the last one to a concurrent creation undoes this one
+        let created_by = request.created_by.to_string();
+        match self.quota_client.assign_seat(&tenant_id, &user.id.to_string(), Some(&created_by)).await {
+            Err(ServiceError::QuotaExceeded(message)) => {
+                let _ = self.user_repo.delete(request.tenant_id, user.id).await;
+                return Err(ServiceError::QuotaExceeded(message));
+            }
+            Err(e) => tracing::warn!("Could not assign a seat to user {}: {}", user.id, e),
+            Ok(()) => {}
+        }
+        
         // Create profile if provided
         let profile = if let Some(profile_request) = request.user_request.profile {
             Some(self.profile_repo.create(request.tenant_id, user.id, profile_request).await?)
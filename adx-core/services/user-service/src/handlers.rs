@@ -7,7 +7,7 @@ use axum::{
 use serde::{Deserialize, Serialize};
 use std::sync::Arc;
 use uuid::Uuid;
-use adx_shared::{TenantContext, UserContext, Result};
+use adx_shared::{quotas::QuotaClient, ServiceError, TenantContext, UserContext, Result};
 use crate::{
     models::*,
     repositories::*,
@@ -23,6 +23,7 @@ pub struct UserServiceState {
     pub preference_repo: Arc<dyn UserPreferenceRepository>,
     pub activity_repo: Arc<dyn UserActivityRepository>,
     pub validator: Arc<UserValidator>,
+    pub quota_client: Arc<QuotaClient>,
 }
 
 // Query parameters for listing users
@@ -162,9 +163,29 @@ pub async fn create_user(
         return Ok(Json(ApiResponse::error("User with this email already exists".to_string())));
     }
     
+    // Check the tenant has a seat for the user. An unreachable license
+    // service does not block creation; the nightly seat reconciliation
+    // corrects the count.
+    match state.quota_client.require_seat(&tenant_context.tenant_id).await {
+        Err(ServiceError::QuotaExceeded(message)) => return Ok(Json(ApiResponse::error(message))),
+        Err(e) => tracing::warn!("Could not check seats of tenant {}: {}", tenant_uuid, e),
+        Ok(_) => {}
+    }
+    
     // Create user
     match state.user_repo.create(tenant_uuid, request).await {
         Ok(user) => {
+            // Take the seat; losing the last one to a concurrent creation undoes this one
+            let creator_id = creator_uuid.to_string();
+            match state.quota_client.assign_seat(&tenant_context.tenant_id, &user.id.to_string(), Some(&creator_id)).await {
+                Err(ServiceError::QuotaExceeded(message)) => {
+                    let _ = state.user_repo.delete(tenant_uuid, user.id).await;
+                    return Ok(Json(ApiResponse::error(message)));
+                }
+                Err(e) => tracing::warn!("Could not assign a seat to user {}: {}", user.id, e),
+                Ok(()) => {}
+            }
+            
             // Log activity
             let activity = UserActivityLog {
                 id: Uuid::new_v4(),
@@ -272,6 +293,11 @@ pub async fn delete_user(
     // Delete user
     match state.user_repo.delete(tenant_uuid, user_id).await {
         Ok(_) => {
+            // Free the user's seat
+            if let Err(e) = state.quota_client.unassign_seat(&tenant_context.tenant_id, &user_id.to_string()).await {
+                tracing::warn!("Could not free the seat of user {}: {}", user_id, e);
+            }
+            
             // Log activity
             let activity = UserActivityLog {
                 id: Uuid::new_v4(),
@@ -16,6 +16,7 @@ use adx_shared::{
     config::AppConfig,
     middleware::{tenant_context_middleware, user_context_middleware},
     health::health_check as shared_health_check,
+    quotas::QuotaClient,
 };
 use crate::{
     handlers::*,
@@ -30,6 +31,8 @@ pub async fn create_app(config: &AppConfig, pool: PgPool) -> Router {
     let preference_repo = Arc::new(PostgresUserPreferenceRepository::new(pool.clone()));
     let activity_repo = Arc::new(PostgresUserActivityRepository::new(pool.clone()));
     let validator = Arc::new(UserValidator::new());
+    // gRPC when LICENSE_SERVICE_GRPC_URL is set
+    let quota_client = Arc::new(QuotaClient::from_env().expect("LICENSE_SERVICE_GRPC_URL must be a valid URI"));
     
     // Create application state
     let state = UserServiceState {
@@ -38,6 +41,7 @@ pub async fn create_app(config: &AppConfig, pool: PgPool) -> Router {
         preference_repo,
         activity_repo,
         validator,
+        quota_client,
     };
     
     // Create router with routes
@@ -2,6 +2,7 @@ use std::sync::Arc;
 use sqlx::PgPool;
 use adx_shared::{
     config::AppConfig,
+    quotas::QuotaClient,
     Result, Error,
 };
 use crate::{
@@ -23,6 +24,8 @@ impl UserServiceWorker {
         let preference_repo = Arc::new(PostgresUserPreferenceRepository::new(pool.clone()));
         let activity_repo = Arc::new(PostgresUserActivityRepository::new(pool.clone()));
         let validator = Arc::new(UserValidator::new());
+        // gRPC when LICENSE_SERVICE_GRPC_URL is set
+        let quota_client = Arc::new(QuotaClient::from_env()?);
         
         // Create activities implementation
         let activities = Arc::new(UserServiceActivitiesImpl::new(
@@ -31,6 +34,7 @@ impl UserServiceWorker {
             preference_repo,
             activity_repo,
             validator,
+            quota_client,
         ));
         
         Ok(Self {
@@ -0,0 +1,45 @@
+-- Custom domain onboarding
+-- A domain is verified with a TXT record proving ownership and a CNAME
+-- pointing it at the platform edge, then gets a Let's Encrypt certificate
+-- issued over ACME HTTP-01. Certificates are kept here, private keys
+-- encrypted, and served to api-gateway for SNI.
+
+ALTER TABLE custom_domains
+    ADD COLUMN verification_attempts INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN last_checked_at TIMESTAMPTZ,
+    ADD COLUMN last_verification_error TEXT;
+
+-- A domain has one current certificate; renewal replaces it in place
+CREATE UNIQUE INDEX idx_ssl_certificates_domain_unique ON ssl_certificates(domain_id);
+
+ALTER TABLE ssl_certificates
+    -- Leaf certificate followed by its chain
+    ADD COLUMN certificate_pem TEXT,
+    ADD COLUMN private_key_encrypted TEXT,
+    ADD COLUMN renewed_at TIMESTAMPTZ,
+    ADD COLUMN renewal_attempts INTEGER NOT NULL DEFAULT 0,
+    ADD COLUMN last_renewal_error TEXT;
+
+CREATE INDEX idx_ssl_certificates_renewal ON ssl_certificates(expires_at)
+    WHERE status = 'issued' AND auto_renewal;
+
+-- ACME account per directory, credentials encrypted
+CREATE TABLE acme_accounts (
+    directory_url TEXT PRIMARY KEY,
+    credentials_encrypted TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+-- Pending HTTP-01 challenges, answered on /.well-known/acme-challenge/{token}
+CREATE TABLE acme_challenges (
+    token VARCHAR(255) PRIMARY KEY,
+    domain VARCHAR(255) NOT NULL,
+    key_authorization TEXT NOT NULL,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    expires_at TIMESTAMPTZ NOT NULL
+);
+
+CREATE INDEX idx_acme_challenges_expires_at ON acme_challenges(expires_at);
+
+COMMENT ON TABLE acme_accounts IS 'ACME accounts certificates are ordered with, one per directory';
+COMMENT ON TABLE acme_challenges IS 'HTTP-01 challenges awaiting validation by the certificate authority';
@@ -0,0 +1,42 @@
+-- Versioned tenant themes
+-- A tenant edits one draft theme at a time and publishes it; the published
+-- version is what the frontend shell loads for the tenant's domains.
+-- Publishing archives the previous version, which can be restored into the
+-- draft later.
+
+-- Branding assets are stored in file-service
+ALTER TABLE branding_assets
+    ADD COLUMN file_id UUID;
+
+CREATE TABLE branding_versions (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id VARCHAR(255) NOT NULL,
+    version INTEGER NOT NULL,
+    status VARCHAR(20) NOT NULL DEFAULT 'draft',
+    brand_name VARCHAR(255) NOT NULL,
+    logo_asset_id UUID REFERENCES branding_assets(id) ON DELETE SET NULL,
+    favicon_asset_id UUID REFERENCES branding_assets(id) ON DELETE SET NULL,
+    color_scheme JSONB NOT NULL,
+    typography JSONB NOT NULL,
+    custom_css TEXT,
+    created_by VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    published_by VARCHAR(255),
+    published_at TIMESTAMPTZ,
+
+    UNIQUE(tenant_id, version),
+    CONSTRAINT branding_versions_status_check
+        CHECK (status IN ('draft', 'published', 'archived'))
+);
+
+-- At most one draft and one published version per tenant
+CREATE UNIQUE INDEX idx_branding_versions_draft ON branding_versions(tenant_id) WHERE status = 'draft';
+CREATE UNIQUE INDEX idx_branding_versions_published ON branding_versions(tenant_id) WHERE status = 'published';
+CREATE INDEX idx_branding_versions_tenant_id ON branding_versions(tenant_id, version DESC);
+
+CREATE TRIGGER update_branding_versions_updated_at
+    BEFORE UPDATE ON branding_versions
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+COMMENT ON TABLE branding_versions IS 'Versioned tenant themes with draft, published and archived states';
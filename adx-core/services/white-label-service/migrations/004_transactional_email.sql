@@ -0,0 +1,93 @@
+-- Branded transactional email
+-- System emails (verification, invitations, dunning) are rendered from the
+-- platform's default templates unless the tenant overrides them, and sent
+-- through the tenant's own SMTP server or SES account when it has one.
+
+-- Tenant overrides of system email templates, per locale
+CREATE TABLE email_templates (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id VARCHAR(255) NOT NULL,
+    template_key VARCHAR(100) NOT NULL,
+    -- Empty for the override used when no locale matches
+    locale VARCHAR(35) NOT NULL DEFAULT '',
+    format VARCHAR(20) NOT NULL DEFAULT 'mjml',
+    subject TEXT NOT NULL,
+    body TEXT NOT NULL,
+    text_body TEXT,
+    enabled BOOLEAN NOT NULL DEFAULT true,
+    updated_by VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    UNIQUE(tenant_id, template_key, locale),
+    CONSTRAINT email_templates_format_check
+        CHECK (format IN ('mjml', 'handlebars'))
+);
+
+CREATE INDEX idx_email_templates_tenant_id ON email_templates(tenant_id);
+
+CREATE TRIGGER update_email_templates_updated_at
+    BEFORE UPDATE ON email_templates
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- A tenant's own mail delivery; secrets are encrypted with the tenant's
+-- field encryption key
+CREATE TABLE tenant_email_settings (
+    tenant_id VARCHAR(255) PRIMARY KEY,
+    provider VARCHAR(20) NOT NULL,
+    from_email VARCHAR(255) NOT NULL,
+    from_name VARCHAR(255),
+    reply_to VARCHAR(255),
+    smtp_host VARCHAR(255),
+    smtp_port INTEGER,
+    smtp_username VARCHAR(255),
+    smtp_password_encrypted TEXT,
+    smtp_starttls BOOLEAN NOT NULL DEFAULT true,
+    ses_region VARCHAR(50),
+    ses_access_key_id VARCHAR(255),
+    ses_secret_access_key_encrypted TEXT,
+    ses_configuration_set VARCHAR(255),
+    updated_by VARCHAR(255),
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    CONSTRAINT tenant_email_settings_provider_check
+        CHECK (provider IN ('smtp', 'ses')),
+    CONSTRAINT tenant_email_settings_smtp_check
+        CHECK (provider <> 'smtp' OR (smtp_host IS NOT NULL AND smtp_port IS NOT NULL)),
+    CONSTRAINT tenant_email_settings_ses_check
+        CHECK (provider <> 'ses' OR (ses_region IS NOT NULL AND ses_access_key_id IS NOT NULL
+                                     AND ses_secret_access_key_encrypted IS NOT NULL))
+);
+
+CREATE TRIGGER update_tenant_email_settings_updated_at
+    BEFORE UPDATE ON tenant_email_settings
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+-- Sent emails, so a retried send request is delivered once. A delivery is
+-- claimed as 'sending' before the mail server is contacted.
+CREATE TABLE email_deliveries (
+    id UUID PRIMARY KEY DEFAULT gen_random_uuid(),
+    tenant_id VARCHAR(255),
+    template_key VARCHAR(100) NOT NULL,
+    recipients TEXT[] NOT NULL,
+    idempotency_key VARCHAR(255),
+    status VARCHAR(20) NOT NULL DEFAULT 'sending',
+    provider VARCHAR(20) NOT NULL,
+    message_id VARCHAR(255),
+    tags TEXT[] NOT NULL DEFAULT '{}',
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    sent_at TIMESTAMPTZ,
+
+    CONSTRAINT email_deliveries_status_check
+        CHECK (status IN ('sending', 'sent'))
+);
+
+CREATE UNIQUE INDEX idx_email_deliveries_idempotency_key
+    ON email_deliveries(COALESCE(tenant_id, ''), idempotency_key)
+    WHERE idempotency_key IS NOT NULL;
+CREATE INDEX idx_email_deliveries_tenant_id ON email_deliveries(tenant_id, created_at DESC);
+
+COMMENT ON TABLE email_templates IS 'Tenant overrides of system email templates';
+COMMENT ON TABLE tenant_email_settings IS 'Per-tenant SMTP or SES delivery settings';
+COMMENT ON TABLE email_deliveries IS 'Delivered system emails, keyed for idempotent sends';
@@ -0,0 +1,24 @@
+-- Hosted login page configuration
+-- A tenant edits a draft of its login and registration pages, previews it
+-- and publishes it; auth-service's hosted login pages render the published
+-- configuration for the tenant's verified domains.
+
+CREATE TABLE login_page_configs (
+    tenant_id VARCHAR(255) PRIMARY KEY,
+    draft JSONB,
+    draft_updated_by VARCHAR(255),
+    draft_updated_at TIMESTAMPTZ,
+    published JSONB,
+    -- Incremented on each publish
+    version INTEGER NOT NULL DEFAULT 0,
+    published_by VARCHAR(255),
+    published_at TIMESTAMPTZ,
+    created_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE TRIGGER update_login_page_configs_updated_at
+    BEFORE UPDATE ON login_page_configs
+    FOR EACH ROW EXECUTE FUNCTION update_updated_at_column();
+
+COMMENT ON TABLE login_page_configs IS 'Draft and published hosted login page configuration per tenant';
@@ -0,0 +1,16 @@
+-- Reseller customer tenants
+-- A reseller owns a group of customer tenants. Customers inherit the
+-- branding of their reseller chain until they publish their own theme, and
+-- the reseller's admins manage them through the partner API.
+
+CREATE TABLE reseller_tenants (
+    reseller_id UUID NOT NULL REFERENCES reseller_hierarchies(id) ON DELETE CASCADE,
+    -- A tenant belongs to at most one reseller
+    tenant_id VARCHAR(255) PRIMARY KEY,
+    added_by VARCHAR(255),
+    added_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX idx_reseller_tenants_reseller_id ON reseller_tenants(reseller_id);
+
+COMMENT ON TABLE reseller_tenants IS 'Customer tenants owned by a reseller';
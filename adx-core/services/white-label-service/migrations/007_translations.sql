@@ -0,0 +1,31 @@
+-- Per-tenant translations
+-- Tenants override UI strings and email copy per locale and namespace. The
+-- frontend layers a tenant's resolved strings over its bundled translations;
+-- system email templates read the `email` namespace.
+
+CREATE TABLE translation_overrides (
+    tenant_id VARCHAR(255) NOT NULL,
+    locale VARCHAR(35) NOT NULL,
+    namespace VARCHAR(64) NOT NULL,
+    message_key VARCHAR(255) NOT NULL,
+    value TEXT NOT NULL,
+    updated_by VARCHAR(255),
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW(),
+
+    PRIMARY KEY (tenant_id, locale, namespace, message_key)
+);
+
+-- How a tenant's locales fall back to one another
+CREATE TABLE translation_settings (
+    tenant_id VARCHAR(255) PRIMARY KEY,
+    default_locale VARCHAR(35) NOT NULL DEFAULT 'en',
+    -- Locale to the locales it falls back to, e.g. {"pt-br": ["pt-pt"]}
+    fallbacks JSONB NOT NULL DEFAULT '{}',
+    -- Incremented on every change to the tenant's translations; the public
+    -- endpoint's ETag
+    revision BIGINT NOT NULL DEFAULT 0,
+    updated_at TIMESTAMPTZ NOT NULL DEFAULT NOW()
+);
+
+COMMENT ON TABLE translation_overrides IS 'Tenant overrides of UI strings and email copy, per locale and namespace';
+COMMENT ON TABLE translation_settings IS 'Default locale, fallback chains and translation revision per tenant';
@@ -1,12 +1,15 @@
 use crate::config::WhiteLabelConfig;
 use crate::error::{WhiteLabelError, WhiteLabelResult};
+use crate::services::dns_service::onboarding_records;
+use crate::services::ssl_service::ProvisionedCertificate;
 use crate::services::{
     AssetService, DnsService, EmailService, SslService, StorageService,
 };
 use crate::types::*;
 use crate::workflows::*;
+use adx_shared::database::encryption::FieldCipher;
 use async_trait::async_trait;
-use chrono::Utc;
+use chrono::{DateTime, Duration, Utc};
 use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use std::collections::HashMap;
@@ -22,6 +25,7 @@ pub struct WhiteLabelActivities {
     asset_service: Arc<AssetService>,
     email_service: Arc<EmailService>,
     storage_service: Arc<StorageService>,
+    cipher: Arc<FieldCipher>,
 }
 
 impl WhiteLabelActivities {
@@ -33,6 +37,7 @@ impl WhiteLabelActivities {
         asset_service: Arc<AssetService>,
         email_service: Arc<EmailService>,
         storage_service: Arc<StorageService>,
+        cipher: Arc<FieldCipher>,
     ) -> Self {
         Self {
             db_pool,
@@ -42,6 +47,7 @@ impl WhiteLabelActivities {
             asset_service,
             email_service,
             storage_service,
+            cipher,
         }
     }
 
@@ -147,22 +153,11 @@ impl WhiteLabelActivities {
         &self,
         request: GenerateDnsRecordsRequest,
     ) -> WhiteLabelResult<Vec<DnsRecord>> {
-        let records = vec![
-            DnsRecord {
-                record_type: "TXT".to_string(),
-                name: format!("_adx-verification.{}", request.domain),
-                value: request.verification_token,
-                ttl: 300,
-            },
-            DnsRecord {
-                record_type: "CNAME".to_string(),
-                name: request.domain.clone(),
-                value: "adx-core-lb.example.com".to_string(), // Load balancer endpoint
-                ttl: 300,
-            },
-        ];
-
-        Ok(records)
+        Ok(onboarding_records(
+            &request.domain,
+            &request.verification_token,
+            &self.config.domain_config.cname_target,
+        ))
     }
 
     // #[activity] - would use temporal activity attribute when available
@@ -173,6 +168,28 @@ impl WhiteLabelActivities {
         self.dns_service.verify_records(&request.domain, &request.expected_records).await
     }
 
+    // #[activity] - would use temporal activity attribute when available
+    pub async fn record_domain_verification(
+        &self,
+        request: RecordDomainVerificationRequest,
+    ) -> WhiteLabelResult<()> {
+        sqlx::query!(
+            r#"
+            UPDATE custom_domains
+            SET status = $1, verification_attempts = verification_attempts + 1,
+                last_checked_at = NOW(), last_verification_error = $2
+            WHERE id = $3
+            "#,
+            DomainStatus::Verifying as DomainStatus,
+            request.error_message,
+            request.domain_id
+        )
+        .execute(&*self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
     // #[activity] - would use temporal activity attribute when available
     pub async fn update_domain_status(
         &self,
@@ -195,7 +212,69 @@ impl WhiteLabelActivities {
         &self,
         request: ProvisionSslRequest,
     ) -> WhiteLabelResult<SslCertificateResult> {
-        self.ssl_service.provision_certificate(&request.domain).await
+        let provisioned = self.ssl_service.provision_certificate(&request.domain).await?;
+        self.store_certificate(request.domain_id, &request.tenant_id, &provisioned).await?;
+
+        Ok(provisioned.result)
+    }
+
+    // #[activity] - would use temporal activity attribute when available
+    pub async fn list_certificates_due_for_renewal(
+        &self,
+        request: ListCertificateRenewalsRequest,
+    ) -> WhiteLabelResult<Vec<CertificateRenewalTarget>> {
+        let renew_before = Utc::now() + Duration::days(request.renewal_days_before_expiry as i64);
+
+        let targets = sqlx::query_as!(
+            CertificateRenewalTarget,
+            r#"
+            SELECT d.id as domain_id, d.tenant_id, d.domain, c.certificate_id, c.expires_at as "expires_at!"
+            FROM ssl_certificates c
+            JOIN custom_domains d ON d.id = c.domain_id
+            WHERE c.status = 'issued' AND c.auto_renewal AND c.expires_at < $1
+              AND d.status = 'verified'
+            ORDER BY c.expires_at
+            LIMIT $2
+            "#,
+            renew_before,
+            request.limit as i64
+        )
+        .fetch_all(&*self.db_pool)
+        .await?;
+
+        Ok(targets)
+    }
+
+    // #[activity] - would use temporal activity attribute when available
+    pub async fn renew_ssl_certificate(
+        &self,
+        request: CertificateRenewalTarget,
+    ) -> WhiteLabelResult<SslCertificateResult> {
+        match self
+            .ssl_service
+            .renew_certificate(&request.domain, &request.certificate_id)
+            .await
+        {
+            Ok(provisioned) => {
+                self.store_certificate(request.domain_id, &request.tenant_id, &provisioned).await?;
+                Ok(provisioned.result)
+            }
+            Err(e) => {
+                // The current certificate stays in place until it expires
+                sqlx::query!(
+                    r#"
+                    UPDATE ssl_certificates
+                    SET renewal_attempts = renewal_attempts + 1, last_renewal_error = $1, updated_at = NOW()
+                    WHERE domain_id = $2
+                    "#,
+                    e.to_string(),
+                    request.domain_id
+                )
+                .execute(&*self.db_pool)
+                .await?;
+                Err(e)
+            }
+        }
     }
 
     // #[activity] - would use temporal activity attribute when available
@@ -203,8 +282,7 @@ impl WhiteLabelActivities {
         &self,
         request: ConfigureDomainRoutingRequest,
     ) -> WhiteLabelResult<()> {
-        // Configure load balancer routing
-        // This would integrate with your load balancer (e.g., AWS ALB, Nginx, Traefik)
+        // api-gateway picks routes up from /internal/domain-routes on its next refresh
         tracing::info!(
             "Configuring domain routing for {} -> tenant {}",
             request.domain,
@@ -212,7 +290,7 @@ impl WhiteLabelActivities {
         );
 
         // Update domain record with SSL certificate
-        if let Some(ssl_cert_id) = request.ssl_certificate_id {
+        if let Some(ref ssl_cert_id) = request.ssl_certificate_id {
             sqlx::query!(
                 "UPDATE custom_domains SET ssl_certificate_id = $1 WHERE domain = $2",
                 ssl_cert_id,
@@ -222,6 +300,26 @@ impl WhiteLabelActivities {
             .await?;
         }
 
+        sqlx::query!(
+            r#"
+            INSERT INTO domain_routing_configs (domain_id, tenant_id, ssl_certificate_id, auto_redirect)
+            SELECT d.id, $2, c.id, $3
+            FROM custom_domains d
+            LEFT JOIN ssl_certificates c ON c.domain_id = d.id AND c.certificate_id = $4
+            WHERE d.domain = $1
+            ON CONFLICT (domain_id) DO UPDATE
+            SET ssl_certificate_id = EXCLUDED.ssl_certificate_id,
+                auto_redirect = EXCLUDED.auto_redirect,
+                updated_at = NOW()
+            "#,
+            request.domain,
+            request.tenant_id,
+            request.auto_redirect,
+            request.ssl_certificate_id
+        )
+        .execute(&*self.db_pool)
+        .await?;
+
         Ok(())
     }
 
@@ -726,6 +824,71 @@ impl WhiteLabelActivities {
         Ok(css)
     }
 
+    /// Save a domain's certificate, replacing the previous one. The private
+    /// key is encrypted with the tenant's key.
+    async fn store_certificate(
+        &self,
+        domain_id: Uuid,
+        tenant_id: &str,
+        provisioned: &ProvisionedCertificate,
+    ) -> WhiteLabelResult<()> {
+        let private_key_encrypted = match provisioned.private_key_pem {
+            Some(ref private_key_pem) => Some(
+                self.cipher
+                    .encrypt(tenant_id, "private_key", private_key_pem)
+                    .await
+                    .map_err(|e| WhiteLabelError::Internal(format!("Private key encryption failed: {}", e)))?,
+            ),
+            None => None,
+        };
+        let result = &provisioned.result;
+
+        sqlx::query!(
+            r#"
+            INSERT INTO ssl_certificates (
+                domain_id, certificate_id, certificate_arn, provider, status, issued_at, expires_at,
+                auto_renewal, certificate_pem, private_key_encrypted
+            )
+            VALUES ($1, $2, $3, $4, $5, NOW(), $6, $7, $8, $9)
+            ON CONFLICT (domain_id) DO UPDATE
+            SET certificate_id = EXCLUDED.certificate_id,
+                certificate_arn = EXCLUDED.certificate_arn,
+                provider = EXCLUDED.provider,
+                status = EXCLUDED.status,
+                issued_at = EXCLUDED.issued_at,
+                expires_at = EXCLUDED.expires_at,
+                auto_renewal = EXCLUDED.auto_renewal,
+                certificate_pem = EXCLUDED.certificate_pem,
+                private_key_encrypted = EXCLUDED.private_key_encrypted,
+                renewed_at = NOW(),
+                renewal_attempts = 0,
+                last_renewal_error = NULL,
+                updated_at = NOW()
+            "#,
+            domain_id,
+            result.certificate_id,
+            result.certificate_arn,
+            self.config.ssl_config.provider,
+            result.status.clone() as SslStatus,
+            result.expires_at,
+            result.auto_renewal,
+            provisioned.certificate_pem,
+            private_key_encrypted
+        )
+        .execute(&*self.db_pool)
+        .await?;
+
+        sqlx::query!(
+            "UPDATE custom_domains SET ssl_certificate_id = $1 WHERE id = $2",
+            result.certificate_id,
+            domain_id
+        )
+        .execute(&*self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
     async fn calculate_hierarchy_level(&self, parent_id: Uuid) -> WhiteLabelResult<u32> {
         let result = sqlx::query!(
             r#"
@@ -758,6 +921,15 @@ pub struct DomainValidationResult {
     pub error_message: Option<String>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertificateRenewalTarget {
+    pub domain_id: Uuid,
+    pub tenant_id: String,
+    pub domain: String,
+    pub certificate_id: String,
+    pub expires_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BrandingValidationResult {
     pub is_valid: bool,
@@ -12,6 +12,12 @@ pub struct WhiteLabelConfig {
     pub dns_providers: HashMap<String, DnsProviderConfig>,
     pub email_config: EmailConfig,
     pub storage_config: StorageConfig,
+    /// Token api-gateway presents to read domain routes and certificates
+    pub internal_api_token: String,
+    /// Where branding assets are stored
+    pub file_service_url: String,
+    /// Reports usage and billing rolled up across a reseller's customers
+    pub license_service_url: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -21,6 +27,8 @@ pub struct DomainConfig {
     pub allowed_tlds: Vec<String>,
     pub blocked_domains: Vec<String>,
     pub dns_propagation_wait_seconds: u64,
+    /// Where tenants point their domain's CNAME; the platform edge
+    pub cname_target: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,6 +38,10 @@ pub struct SslConfig {
     pub renewal_days_before_expiry: u32,
     pub certificate_authority: String,
     pub key_size: u32,
+    /// Contact registered with the ACME account, for expiry notices
+    pub contact_email: Option<String>,
+    /// Certificates renewed per renewal workflow run
+    pub renewal_batch_size: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -68,6 +80,9 @@ pub struct EmailConfig {
     pub from_email: String,
     pub from_name: String,
     pub template_cache_ttl_seconds: u64,
+    /// Where logos in emails are loaded from for tenants without a
+    /// verified custom domain
+    pub public_base_url: String,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -102,6 +117,7 @@ impl Default for WhiteLabelConfig {
                     "0.0.0.0".to_string(),
                 ],
                 dns_propagation_wait_seconds: 60,
+                cname_target: "edge.adxcore.com".to_string(),
             },
             ssl_config: SslConfig {
                 provider: "letsencrypt".to_string(),
@@ -109,6 +125,8 @@ impl Default for WhiteLabelConfig {
                 renewal_days_before_expiry: 30,
                 certificate_authority: "https://acme-v02.api.letsencrypt.org/directory".to_string(),
                 key_size: 2048,
+                contact_email: None,
+                renewal_batch_size: 50,
             },
             asset_config: AssetConfig {
                 max_file_size_mb: 10,
@@ -138,6 +156,7 @@ impl Default for WhiteLabelConfig {
                 from_email: "noreply@adxcore.com".to_string(),
                 from_name: "ADX Core".to_string(),
                 template_cache_ttl_seconds: 3600,
+                public_base_url: "https://app.adxcore.com".to_string(),
             },
             storage_config: StorageConfig {
                 provider: "local".to_string(),
@@ -147,6 +166,9 @@ impl Default for WhiteLabelConfig {
                 secret_key: None,
                 endpoint: None,
             },
+            internal_api_token: String::new(),
+            file_service_url: "http://localhost:8083".to_string(),
+            license_service_url: "http://localhost:8087".to_string(),
         }
     }
 }
@@ -162,6 +184,9 @@ impl WhiteLabelConfig {
         cfg.set_default("database_url", default_config.database_url)?;
         cfg.set_default("temporal_server_url", default_config.temporal_server_url)?;
         cfg.set_default("server_port", default_config.server_port)?;
+        cfg.set_default("internal_api_token", default_config.internal_api_token)?;
+        cfg.set_default("file_service_url", default_config.file_service_url)?;
+        cfg.set_default("license_service_url", default_config.license_service_url)?;
 
         cfg.try_deserialize()
     }
@@ -1,10 +1,20 @@
+use crate::config::WhiteLabelConfig;
 use crate::error::{WhiteLabelError, WhiteLabelResult};
+use crate::services::dns_service::onboarding_records;
+use crate::services::email_templates::default_template;
+use crate::services::{
+    DnsService, LoginPageService, ResellerService, ThemeService, TransactionalEmailService, TranslationService,
+};
 use crate::types::*;
 use crate::workflows::*;
+use adx_shared::custom_domains::{DomainRoute, INTERNAL_TOKEN_HEADER};
+use adx_shared::database::encryption::FieldCipher;
+use adx_shared::email::{SendEmailRequest, SendEmailResponse, SystemEmail};
+use adx_shared::login_page::LoginPageConfig;
 use axum::{
-    extract::{Path, Query, State},
-    http::StatusCode,
-    response::Json,
+    extract::{Multipart, Path, Query, State},
+    http::{header, HeaderMap, StatusCode},
+    response::{Html, IntoResponse, Json, Redirect, Response},
     routing::{delete, get, post, put},
     Router,
 };
@@ -12,6 +22,7 @@ use serde::{Deserialize, Serialize};
 use sqlx::PgPool;
 use std::collections::HashMap;
 use std::sync::Arc;
+use subtle::ConstantTimeEq;
 use crate::temporal_mock::TemporalClient;
 use uuid::Uuid;
 
@@ -19,6 +30,14 @@ use uuid::Uuid;
 pub struct AppState {
     pub db_pool: Arc<PgPool>,
     pub temporal_client: Arc<TemporalClient>,
+    pub config: Arc<WhiteLabelConfig>,
+    pub dns_service: Arc<DnsService>,
+    pub cipher: Arc<FieldCipher>,
+    pub theme_service: Arc<ThemeService>,
+    pub transactional_email: Arc<TransactionalEmailService>,
+    pub login_page_service: Arc<LoginPageService>,
+    pub reseller_service: Arc<ResellerService>,
+    pub translation_service: Arc<TranslationService>,
 }
 
 pub fn create_routes() -> Router<AppState> {
@@ -29,7 +48,17 @@ pub fn create_routes() -> Router<AppState> {
         .route("/domains/:domain_id", get(get_custom_domain))
         .route("/domains/:domain_id", delete(delete_custom_domain))
         .route("/domains/:domain_id/verify", post(verify_custom_domain))
+        .route("/domains/:domain_id/dns-records", get(get_domain_dns_records))
         .route("/domains/:domain_id/ssl", post(provision_ssl_certificate))
+
+        // ACME HTTP-01 challenges, proxied here by api-gateway for every host
+        .route("/acme-challenge/:token", get(get_acme_challenge))
+
+        // Service-to-service routes
+        .route("/internal/domain-routes", get(list_domain_routes))
+
+        // System email sending for other services, see adx_shared::email
+        .route("/internal/emails/send", post(send_system_email))
         
         // Branding management routes
         .route("/branding", post(create_white_label_branding))
@@ -38,6 +67,22 @@ pub fn create_routes() -> Router<AppState> {
         .route("/branding", delete(delete_white_label_branding))
         .route("/branding/preview", get(get_branding_preview))
         .route("/branding/rollback", post(rollback_branding))
+
+        // Versioned themes
+        .route("/branding/versions", get(list_branding_versions))
+        .route("/branding/versions/:version_id", get(get_branding_version))
+        .route("/branding/versions/:version_id/restore", post(restore_branding_version))
+        .route("/branding/draft", get(get_branding_draft))
+        .route("/branding/draft", put(save_branding_draft))
+        .route("/branding/draft", delete(discard_branding_draft))
+        .route("/branding/draft/publish", post(publish_branding_draft))
+
+        // Hosted login page configuration
+        .route("/login-page", get(get_login_page))
+        .route("/login-page/draft", put(save_login_page_draft))
+        .route("/login-page/draft", delete(discard_login_page_draft))
+        .route("/login-page/draft/publish", post(publish_login_page_draft))
+        .route("/login-page/preview", post(preview_login_page))
         
         // Reseller management routes
         .route("/resellers", post(create_reseller))
@@ -46,12 +91,47 @@ pub fn create_routes() -> Router<AppState> {
         .route("/resellers/:reseller_id", put(update_reseller))
         .route("/resellers/:reseller_id", delete(delete_reseller))
         .route("/resellers/:reseller_id/hierarchy", get(get_reseller_hierarchy))
+        .route("/resellers/:reseller_id/tenants", get(list_reseller_tenants))
+        .route("/resellers/:reseller_id/tenants", post(assign_reseller_tenant))
+        .route("/resellers/:reseller_id/tenants/:tenant_id", delete(remove_reseller_tenant))
+
+        // Partner routes, for a reseller's admins to manage its customers
+        .route("/partner/reseller", get(get_partner_reseller))
+        .route("/partner/resellers", get(list_partner_sub_resellers))
+        .route("/partner/tenants", get(list_partner_tenants))
+        .route("/partner/usage", get(get_partner_usage))
+        .route("/partner/tenants/:tenant_id/branding", get(get_customer_branding))
+        .route("/partner/tenants/:tenant_id/branding/draft", put(save_customer_branding_draft))
+        .route("/partner/tenants/:tenant_id/branding/draft", delete(discard_customer_branding_draft))
+        .route("/partner/tenants/:tenant_id/branding/draft/publish", post(publish_customer_branding_draft))
+        .route("/partner/tenants/:tenant_id/assets", post(upload_customer_branding_asset))
         
         // Asset management routes
         .route("/assets", post(upload_branding_asset))
         .route("/assets", get(list_branding_assets))
         .route("/assets/:asset_id", get(get_branding_asset))
         .route("/assets/:asset_id", delete(delete_branding_asset))
+
+        // Transactional email templates and delivery settings
+        .route("/email/templates", get(list_email_templates))
+        .route("/email/templates/:template", put(save_email_template))
+        .route("/email/templates/:template", delete(delete_email_template))
+        .route("/email/templates/:template/default", get(get_default_email_template))
+        .route("/email/templates/:template/preview", post(preview_email_template))
+        .route("/email/settings", get(get_email_settings))
+        .route("/email/settings", put(update_email_settings))
+        .route("/email/settings", delete(delete_email_settings))
+        .route("/email/settings/test", post(send_test_email))
+
+        // Translation overrides of UI strings and email copy
+        .route("/translations", get(list_translations))
+        .route("/translations/settings", get(get_translation_settings))
+        .route("/translations/settings", put(update_translation_settings))
+        .route("/translations/export", get(export_translations))
+        .route("/translations/import", post(import_translations))
+        .route("/translations/:locale", delete(delete_locale_translations))
+        .route("/translations/:locale/:namespace", get(get_resolved_translations))
+        .route("/translations/:locale/:namespace", put(save_translations))
         
         // Workflow status routes
         .route("/workflows/:operation_id/status", get(get_workflow_status))
@@ -61,7 +141,51 @@ pub fn create_routes() -> Router<AppState> {
         .route("/health", get(health_check))
 }
 
+/// Unauthenticated routes the frontend shell loads before anyone signs in
+pub fn create_public_routes() -> Router<AppState> {
+    Router::new()
+        .route("/assets/:asset_id", get(get_public_branding_asset))
+        .route("/:domain", get(get_public_branding))
+        .route("/:domain/login-page", get(get_public_login_page))
+        .route("/:domain/translations/:locale/:namespace", get(get_public_translations))
+}
+
 // Domain management handlers
+
+/// The calling tenant, set by api-gateway
+fn tenant_from_headers(headers: &HeaderMap) -> WhiteLabelResult<String> {
+    headers
+        .get("X-Tenant-ID")
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+        .ok_or_else(|| WhiteLabelError::Unauthorized("Missing tenant context".to_string()))
+}
+
+/// The calling user, when api-gateway knows one
+fn user_from_headers(headers: &HeaderMap) -> Option<String> {
+    headers
+        .get("X-User-ID")
+        .and_then(|value| value.to_str().ok())
+        .filter(|value| !value.is_empty())
+        .map(str::to_string)
+}
+
+/// Service-to-service calls carry the shared internal token
+fn require_internal_token(state: &AppState, headers: &HeaderMap) -> WhiteLabelResult<()> {
+    let expected = &state.config.internal_api_token;
+    let provided = headers
+        .get(INTERNAL_TOKEN_HEADER)
+        .and_then(|value| value.to_str().ok())
+        .unwrap_or_default();
+    // Constant time, so the token cannot be guessed byte by byte from
+    // response timings
+    if expected.is_empty() || !bool::from(provided.as_bytes().ct_eq(expected.as_bytes())) {
+        return Err(WhiteLabelError::Unauthorized("Invalid internal token".to_string()));
+    }
+    Ok(())
+}
+
 #[derive(Debug, Deserialize)]
 pub struct CreateCustomDomainRequest {
     pub domain: String,
@@ -72,13 +196,14 @@ pub struct CreateCustomDomainRequest {
 
 pub async fn create_custom_domain(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Json(request): Json<CreateCustomDomainRequest>,
 ) -> WhiteLabelResult<Json<WorkflowResponse>> {
-    let tenant_id = "default_tenant".to_string(); // This would come from auth context
-    
+    let tenant_id = tenant_from_headers(&headers)?;
+
     let workflow_request = CustomDomainSetupRequest {
         tenant_id,
-        domain: request.domain,
+        domain: request.domain.trim().trim_end_matches('.').to_ascii_lowercase(),
         ssl_enabled: request.ssl_enabled,
         auto_redirect: request.auto_redirect,
         dns_provider: request.dns_provider,
@@ -107,7 +232,6 @@ pub async fn create_custom_domain(
 
 #[derive(Debug, Deserialize)]
 pub struct ListDomainsQuery {
-    pub tenant_id: Option<String>,
     pub status: Option<String>,
     pub limit: Option<i64>,
     pub offset: Option<i64>,
@@ -115,59 +239,74 @@ pub struct ListDomainsQuery {
 
 pub async fn list_custom_domains(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Query(query): Query<ListDomainsQuery>,
 ) -> WhiteLabelResult<Json<Vec<CustomDomain>>> {
-    let tenant_id = query.tenant_id.unwrap_or_else(|| "default_tenant".to_string());
+    let tenant_id = tenant_from_headers(&headers)?;
     let limit = query.limit.unwrap_or(50).min(100);
     let offset = query.offset.unwrap_or(0);
 
-    let mut sql = "SELECT id, tenant_id, domain, status, verification_token, ssl_certificate_id, created_at, verified_at, expires_at FROM custom_domains WHERE tenant_id = $1".to_string();
-    let mut params: Vec<Box<dyn sqlx::Encode<'_, sqlx::Postgres> + Send + Sync>> = vec![Box::new(tenant_id)];
-    let mut param_count = 1;
-
-    if let Some(status) = query.status {
-        param_count += 1;
-        sql.push_str(&format!(" AND status = ${}", param_count));
-        params.push(Box::new(status));
-    }
-
-    sql.push_str(&format!(" ORDER BY created_at DESC LIMIT ${} OFFSET ${}", param_count + 1, param_count + 2));
-    params.push(Box::new(limit));
-    params.push(Box::new(offset));
-
-    let domains = sqlx::query_as::<_, crate::models::CustomDomainModel>(&sql)
-        .bind(&params[0])
-        .fetch_all(&*state.db_pool)
-        .await?
-        .into_iter()
-        .map(CustomDomain::from)
-        .collect();
+    let domains = sqlx::query_as::<_, crate::models::CustomDomainModel>(
+        r#"
+        SELECT id, tenant_id, domain, status, verification_token, ssl_certificate_id, created_at, verified_at, expires_at
+        FROM custom_domains
+        WHERE tenant_id = $1 AND ($2::VARCHAR IS NULL OR status = $2)
+        ORDER BY created_at DESC
+        LIMIT $3 OFFSET $4
+        "#,
+    )
+    .bind(tenant_id)
+    .bind(query.status)
+    .bind(limit)
+    .bind(offset)
+    .fetch_all(&*state.db_pool)
+    .await?
+    .into_iter()
+    .map(CustomDomain::from)
+    .collect();
 
     Ok(Json(domains))
 }
 
-pub async fn get_custom_domain(
-    State(state): State<AppState>,
-    Path(domain_id): Path<Uuid>,
-) -> WhiteLabelResult<Json<CustomDomain>> {
-    let domain = sqlx::query_as::<_, crate::models::CustomDomainModel>(
-        "SELECT id, tenant_id, domain, status, verification_token, ssl_certificate_id, created_at, verified_at, expires_at FROM custom_domains WHERE id = $1"
+async fn find_tenant_domain(
+    state: &AppState,
+    tenant_id: &str,
+    domain_id: Uuid,
+) -> WhiteLabelResult<crate::models::CustomDomainModel> {
+    sqlx::query_as::<_, crate::models::CustomDomainModel>(
+        "SELECT id, tenant_id, domain, status, verification_token, ssl_certificate_id, created_at, verified_at, expires_at FROM custom_domains WHERE id = $1 AND tenant_id = $2"
     )
     .bind(domain_id)
+    .bind(tenant_id)
     .fetch_optional(&*state.db_pool)
     .await?
-    .ok_or_else(|| WhiteLabelError::NotFound("Domain not found".to_string()))?;
+    .ok_or_else(|| WhiteLabelError::NotFound("Domain not found".to_string()))
+}
+
+pub async fn get_custom_domain(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(domain_id): Path<Uuid>,
+) -> WhiteLabelResult<Json<CustomDomain>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let domain = find_tenant_domain(&state, &tenant_id, domain_id).await?;
 
     Ok(Json(CustomDomain::from(domain)))
 }
 
 pub async fn delete_custom_domain(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Path(domain_id): Path<Uuid>,
 ) -> WhiteLabelResult<StatusCode> {
-    let result = sqlx::query!("DELETE FROM custom_domains WHERE id = $1", domain_id)
-        .execute(&*state.db_pool)
-        .await?;
+    let tenant_id = tenant_from_headers(&headers)?;
+    let result = sqlx::query!(
+        "DELETE FROM custom_domains WHERE id = $1 AND tenant_id = $2",
+        domain_id,
+        tenant_id
+    )
+    .execute(&*state.db_pool)
+    .await?;
 
     if result.rows_affected() == 0 {
         return Err(WhiteLabelError::NotFound("Domain not found".to_string()));
@@ -176,47 +315,170 @@ pub async fn delete_custom_domain(
     Ok(StatusCode::NO_CONTENT)
 }
 
+/// The TXT and CNAME records the tenant has to create
+pub async fn get_domain_dns_records(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(domain_id): Path<Uuid>,
+) -> WhiteLabelResult<Json<Vec<DnsRecord>>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let domain = find_tenant_domain(&state, &tenant_id, domain_id).await?;
+
+    Ok(Json(onboarding_records(
+        &domain.domain,
+        &domain.verification_token,
+        &state.config.domain_config.cname_target,
+    )))
+}
+
+/// Check the domain's DNS records now rather than waiting for the setup
+/// workflow's next attempt
 pub async fn verify_custom_domain(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Path(domain_id): Path<Uuid>,
 ) -> WhiteLabelResult<Json<DomainVerificationResult>> {
-    // Get domain details
-    let domain = sqlx::query!("SELECT domain FROM custom_domains WHERE id = $1", domain_id)
-        .fetch_optional(&*state.db_pool)
-        .await?
-        .ok_or_else(|| WhiteLabelError::NotFound("Domain not found".to_string()))?;
-
-    // This would trigger DNS verification
-    // For now, return a mock result
-    Ok(Json(DomainVerificationResult {
-        verified: true,
-        verification_method: "DNS".to_string(),
-        dns_records_found: vec![],
-        error_message: None,
-    }))
+    let tenant_id = tenant_from_headers(&headers)?;
+    let domain = find_tenant_domain(&state, &tenant_id, domain_id).await?;
+
+    let expected_records = onboarding_records(
+        &domain.domain,
+        &domain.verification_token,
+        &state.config.domain_config.cname_target,
+    );
+    let result = state.dns_service.verify_records(&domain.domain, &expected_records).await?;
+
+    sqlx::query!(
+        r#"
+        UPDATE custom_domains
+        SET verification_attempts = verification_attempts + 1,
+            last_checked_at = NOW(),
+            last_verification_error = $1,
+            status = CASE WHEN $2 THEN 'verified' ELSE status END,
+            verified_at = CASE WHEN $2 THEN COALESCE(verified_at, NOW()) ELSE verified_at END
+        WHERE id = $3
+        "#,
+        result.error_message,
+        result.verified,
+        domain_id
+    )
+    .execute(&*state.db_pool)
+    .await?;
+
+    Ok(Json(result))
 }
 
+/// Issue (or reissue) the certificate for a verified domain
 pub async fn provision_ssl_certificate(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Path(domain_id): Path<Uuid>,
-) -> WhiteLabelResult<Json<SslCertificateResult>> {
-    // Get domain details
-    let domain = sqlx::query!("SELECT domain FROM custom_domains WHERE id = $1", domain_id)
-        .fetch_optional(&*state.db_pool)
-        .await?
-        .ok_or_else(|| WhiteLabelError::NotFound("Domain not found".to_string()))?;
-
-    // This would trigger SSL certificate provisioning
-    // For now, return a mock result
-    Ok(Json(SslCertificateResult {
-        certificate_id: Uuid::new_v4().to_string(),
-        certificate_arn: None,
-        status: SslStatus::Issued,
-        expires_at: chrono::Utc::now() + chrono::Duration::days(90),
-        auto_renewal: true,
+) -> WhiteLabelResult<Json<WorkflowResponse>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let domain = find_tenant_domain(&state, &tenant_id, domain_id).await?;
+
+    if domain.status != "verified" {
+        return Err(WhiteLabelError::Conflict(
+            "Domain must be verified before a certificate can be issued".to_string(),
+        ));
+    }
+
+    let routing = sqlx::query!(
+        "SELECT auto_redirect FROM domain_routing_configs WHERE domain_id = $1",
+        domain_id
+    )
+    .fetch_optional(&*state.db_pool)
+    .await?;
+
+    let operation_id = Uuid::new_v4().to_string();
+    state
+        .temporal_client
+        .start_workflow(
+            "domain_certificate_workflow",
+            operation_id.clone(),
+            "white-label-task-queue",
+            DomainCertificateRequest {
+                domain_id,
+                tenant_id,
+                domain: domain.domain,
+                auto_redirect: routing.map(|r| r.auto_redirect).unwrap_or(true),
+            },
+        )
+        .await
+        .map_err(|e| WhiteLabelError::Temporal(e.to_string()))?;
+
+    Ok(Json(WorkflowResponse::Started {
+        operation_id,
+        status_url: format!("/api/white-label/workflows/{}/status", operation_id),
+        estimated_duration_seconds: Some(120),
     }))
 }
 
+/// Answer an HTTP-01 challenge while a certificate order is pending
+pub async fn get_acme_challenge(
+    State(state): State<AppState>,
+    Path(token): Path<String>,
+) -> WhiteLabelResult<impl IntoResponse> {
+    let challenge = sqlx::query!(
+        "SELECT key_authorization FROM acme_challenges WHERE token = $1 AND expires_at > NOW()",
+        token
+    )
+    .fetch_optional(&*state.db_pool)
+    .await?
+    .ok_or_else(|| WhiteLabelError::NotFound("Challenge not found".to_string()))?;
+
+    Ok(([(header::CONTENT_TYPE, "text/plain")], challenge.key_authorization))
+}
+
+/// Every verified domain with its certificate, for api-gateway's SNI and
+/// host routing. Carries private keys, so it requires the internal token.
+pub async fn list_domain_routes(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> WhiteLabelResult<Json<Vec<DomainRoute>>> {
+    require_internal_token(&state, &headers)?;
+
+    let rows = sqlx::query!(
+        r#"
+        SELECT d.domain, d.tenant_id, COALESCE(r.auto_redirect, true) as "auto_redirect!",
+               c.certificate_id as "certificate_id?", c.certificate_pem, c.private_key_encrypted,
+               c.expires_at as certificate_expires_at
+        FROM custom_domains d
+        LEFT JOIN domain_routing_configs r ON r.domain_id = d.id
+        LEFT JOIN ssl_certificates c ON c.domain_id = d.id AND c.status = 'issued'
+        WHERE d.status = 'verified'
+        "#
+    )
+    .fetch_all(&*state.db_pool)
+    .await?;
+
+    let mut routes = Vec::with_capacity(rows.len());
+    for row in rows {
+        let private_key_pem = match row.private_key_encrypted {
+            Some(ref encrypted) => Some(
+                state
+                    .cipher
+                    .decrypt(&row.tenant_id, "private_key", encrypted)
+                    .await
+                    .map_err(|e| WhiteLabelError::Internal(format!("Private key decryption failed: {}", e)))?,
+            ),
+            None => None,
+        };
+
+        routes.push(DomainRoute {
+            domain: row.domain,
+            tenant_id: row.tenant_id,
+            auto_redirect: row.auto_redirect,
+            certificate_id: row.certificate_id,
+            certificate_pem: row.certificate_pem,
+            private_key_pem,
+            certificate_expires_at: row.certificate_expires_at,
+        });
+    }
+
+    Ok(Json(routes))
+}
+
 // Branding management handlers
 pub async fn create_white_label_branding(
     State(state): State<AppState>,
@@ -243,10 +505,16 @@ pub async fn create_white_label_branding(
     }))
 }
 
+#[derive(Debug, Deserialize)]
+pub struct BrandingQuery {
+    pub tenant_id: Option<String>,
+}
+
 pub async fn get_white_label_branding(
     State(state): State<AppState>,
+    Query(query): Query<BrandingQuery>,
 ) -> WhiteLabelResult<Json<WhiteLabelBranding>> {
-    let tenant_id = "default_tenant".to_string(); // This would come from auth context
+    let tenant_id = query.tenant_id.unwrap_or_else(|| "default_tenant".to_string());
     
     let branding = sqlx::query_as::<_, crate::models::WhiteLabelBrandingModel>(
         "SELECT id, tenant_id, brand_name, logo_url, favicon_url, primary_color, secondary_color, accent_color, font_family, custom_css, email_templates, created_at, updated_at FROM white_label_branding WHERE tenant_id = $1"
@@ -452,48 +720,231 @@ pub async fn get_reseller_hierarchy(
     Ok(Json(ResellerHierarchyResponse {
         reseller: ResellerHierarchy::from(reseller),
         children,
-        hierarchy_level: 1, // This would be calculated properly
+        hierarchy_level: state.reseller_service.hierarchy_level(reseller_id).await?,
     }))
 }
 
-// Asset management handlers
-#[derive(Debug, Deserialize)]
-pub struct UploadAssetRequest {
-    pub asset_type: AssetType,
-    pub filename: String,
-    pub file_data: Vec<u8>,
+pub async fn list_reseller_tenants(
+    State(state): State<AppState>,
+    Path(reseller_id): Path<Uuid>,
+) -> WhiteLabelResult<Json<Vec<ResellerTenant>>> {
+    state.reseller_service.get(reseller_id).await?;
+    Ok(Json(state.reseller_service.list_tenants(reseller_id).await?))
+}
+
+pub async fn assign_reseller_tenant(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(reseller_id): Path<Uuid>,
+    Json(request): Json<AssignResellerTenantRequest>,
+) -> WhiteLabelResult<(StatusCode, Json<ResellerTenant>)> {
+    let user_id = user_from_headers(&headers);
+
+    let assigned = state
+        .reseller_service
+        .assign_tenant(reseller_id, &request.tenant_id, user_id.as_deref())
+        .await?;
+    Ok((StatusCode::CREATED, Json(assigned)))
+}
+
+pub async fn remove_reseller_tenant(
+    State(state): State<AppState>,
+    Path((reseller_id, tenant_id)): Path<(Uuid, String)>,
+) -> WhiteLabelResult<StatusCode> {
+    state.reseller_service.remove_tenant(reseller_id, &tenant_id).await?;
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Partner handlers
+
+/// The reseller the calling tenant is
+async fn partner_from_headers(state: &AppState, headers: &HeaderMap) -> WhiteLabelResult<ResellerHierarchy> {
+    let tenant_id = tenant_from_headers(headers)?;
+    state.reseller_service.for_tenant(&tenant_id).await
+}
+
+/// Fail unless the calling reseller manages the customer tenant
+async fn require_managed_customer(state: &AppState, headers: &HeaderMap, tenant_id: &str) -> WhiteLabelResult<()> {
+    let reseller = partner_from_headers(state, headers).await?;
+    state.reseller_service.authorize_customer(reseller.id, tenant_id).await
+}
+
+pub async fn get_partner_reseller(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> WhiteLabelResult<Json<ResellerHierarchy>> {
+    Ok(Json(partner_from_headers(&state, &headers).await?))
+}
+
+pub async fn list_partner_sub_resellers(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> WhiteLabelResult<Json<Vec<ResellerHierarchy>>> {
+    let reseller = partner_from_headers(&state, &headers).await?;
+    Ok(Json(state.reseller_service.sub_resellers(reseller.id).await?))
+}
+
+/// Customers of the calling reseller and of its sub-resellers
+pub async fn list_partner_tenants(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> WhiteLabelResult<Json<Vec<ResellerTenant>>> {
+    let reseller = partner_from_headers(&state, &headers).await?;
+    Ok(Json(state.reseller_service.managed_tenants(reseller.id).await?))
+}
+
+/// Licenses, billing and quota usage across the calling reseller's
+/// customers, over the last 30 days by default
+pub async fn get_partner_usage(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<UsageRollupQuery>,
+) -> WhiteLabelResult<Json<UsageRollupReport>> {
+    let reseller = partner_from_headers(&state, &headers).await?;
+    let start_date = query
+        .start_date
+        .unwrap_or_else(|| chrono::Utc::now() - chrono::Duration::days(30));
+    let end_date = query.end_date.unwrap_or_else(chrono::Utc::now);
+
+    let report = state
+        .reseller_service
+        .usage(reseller.id, start_date, end_date)
+        .await?;
+    Ok(Json(report))
+}
+
+pub async fn get_customer_branding(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(tenant_id): Path<String>,
+) -> WhiteLabelResult<Json<CustomerBranding>> {
+    require_managed_customer(&state, &headers, &tenant_id).await?;
+    Ok(Json(state.reseller_service.customer_branding(&tenant_id).await?))
 }
 
+pub async fn save_customer_branding_draft(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(tenant_id): Path<String>,
+    Json(theme): Json<BrandingTheme>,
+) -> WhiteLabelResult<Json<BrandingVersion>> {
+    require_managed_customer(&state, &headers, &tenant_id).await?;
+    let user_id = user_from_headers(&headers);
+
+    let draft = state
+        .theme_service
+        .save_draft(&tenant_id, user_id.as_deref(), theme)
+        .await?;
+    Ok(Json(draft))
+}
+
+pub async fn discard_customer_branding_draft(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(tenant_id): Path<String>,
+) -> WhiteLabelResult<StatusCode> {
+    require_managed_customer(&state, &headers, &tenant_id).await?;
+    state.theme_service.discard_draft(&tenant_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn publish_customer_branding_draft(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(tenant_id): Path<String>,
+) -> WhiteLabelResult<Json<BrandingVersion>> {
+    require_managed_customer(&state, &headers, &tenant_id).await?;
+    let user_id = user_from_headers(&headers);
+
+    let published = state
+        .theme_service
+        .publish_draft(&tenant_id, user_id.as_deref())
+        .await?;
+    Ok(Json(published))
+}
+
+/// Upload a logo or favicon owned by the customer, for its theme
+pub async fn upload_customer_branding_asset(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(tenant_id): Path<String>,
+    multipart: Multipart,
+) -> WhiteLabelResult<(StatusCode, Json<BrandingAsset>)> {
+    require_managed_customer(&state, &headers, &tenant_id).await?;
+    let (asset_type, filename, data) = read_asset_upload(multipart).await?;
+
+    let asset = state
+        .theme_service
+        .upload_asset(&tenant_id, asset_type, &filename, &data)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(asset)))
+}
+
+// Asset management handlers
+
+/// Multipart upload with an `asset_type` field and a `file` field
 pub async fn upload_branding_asset(
     State(state): State<AppState>,
-    Json(request): Json<UploadAssetRequest>,
-) -> WhiteLabelResult<Json<BrandingAsset>> {
-    let tenant_id = "default_tenant".to_string(); // This would come from auth context
-    
-    // This would process the asset upload
-    // For now, return a mock response
-    Ok(Json(BrandingAsset {
-        id: Uuid::new_v4(),
-        tenant_id,
-        asset_type: request.asset_type,
-        original_filename: request.filename,
-        file_path: "assets/mock/path.png".to_string(),
-        file_size: request.file_data.len() as u64,
-        mime_type: "image/png".to_string(),
-        dimensions: Some(AssetDimensions {
-            width: 256,
-            height: 256,
-        }),
-        checksum: "mock_checksum".to_string(),
-        created_at: chrono::Utc::now(),
-    }))
+    headers: HeaderMap,
+    multipart: Multipart,
+) -> WhiteLabelResult<(StatusCode, Json<BrandingAsset>)> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let (asset_type, filename, data) = read_asset_upload(multipart).await?;
+
+    let asset = state
+        .theme_service
+        .upload_asset(&tenant_id, asset_type, &filename, &data)
+        .await?;
+
+    Ok((StatusCode::CREATED, Json(asset)))
+}
+
+/// The asset type, filename and content of an asset upload
+async fn read_asset_upload(mut multipart: Multipart) -> WhiteLabelResult<(AssetType, String, axum::body::Bytes)> {
+    let mut asset_type = None;
+    let mut file = None;
+    while let Some(field) = multipart
+        .next_field()
+        .await
+        .map_err(|e| WhiteLabelError::Validation(format!("Invalid multipart body: {}", e)))?
+    {
+        match field.name() {
+            Some("asset_type") => {
+                let value = field
+                    .text()
+                    .await
+                    .map_err(|e| WhiteLabelError::Validation(format!("Invalid asset_type: {}", e)))?;
+                asset_type = Some(
+                    serde_json::from_value::<AssetType>(serde_json::Value::String(value.clone()))
+                        .map_err(|_| WhiteLabelError::Validation(format!("Unknown asset type {}", value)))?,
+                );
+            }
+            Some("file") => {
+                let filename = field.file_name().unwrap_or("asset").to_string();
+                let data = field
+                    .bytes()
+                    .await
+                    .map_err(|e| WhiteLabelError::Validation(format!("Invalid file: {}", e)))?;
+                file = Some((filename, data));
+            }
+            _ => {}
+        }
+    }
+
+    let asset_type = asset_type.ok_or_else(|| WhiteLabelError::Validation("asset_type is required".to_string()))?;
+    let (filename, data) = file.ok_or_else(|| WhiteLabelError::Validation("file is required".to_string()))?;
+
+    Ok((asset_type, filename, data))
 }
 
 pub async fn list_branding_assets(
     State(state): State<AppState>,
+    headers: HeaderMap,
 ) -> WhiteLabelResult<Json<Vec<BrandingAsset>>> {
-    let tenant_id = "default_tenant".to_string(); // This would come from auth context
-    
+    let tenant_id = tenant_from_headers(&headers)?;
+
     let assets = sqlx::query_as::<_, crate::models::BrandingAssetModel>(
         "SELECT id, tenant_id, asset_type, original_filename, file_path, file_size, mime_type, dimensions_width, dimensions_height, checksum, created_at FROM branding_assets WHERE tenant_id = $1 ORDER BY created_at DESC"
     )
@@ -509,12 +960,16 @@ pub async fn list_branding_assets(
 
 pub async fn get_branding_asset(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Path(asset_id): Path<Uuid>,
 ) -> WhiteLabelResult<Json<BrandingAsset>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+
     let asset = sqlx::query_as::<_, crate::models::BrandingAssetModel>(
-        "SELECT id, tenant_id, asset_type, original_filename, file_path, file_size, mime_type, dimensions_width, dimensions_height, checksum, created_at FROM branding_assets WHERE id = $1"
+        "SELECT id, tenant_id, asset_type, original_filename, file_path, file_size, mime_type, dimensions_width, dimensions_height, checksum, created_at FROM branding_assets WHERE id = $1 AND tenant_id = $2"
     )
     .bind(asset_id)
+    .bind(tenant_id)
     .fetch_optional(&*state.db_pool)
     .await?
     .ok_or_else(|| WhiteLabelError::NotFound("Asset not found".to_string()))?;
@@ -524,19 +979,464 @@ pub async fn get_branding_asset(
 
 pub async fn delete_branding_asset(
     State(state): State<AppState>,
+    headers: HeaderMap,
     Path(asset_id): Path<Uuid>,
 ) -> WhiteLabelResult<StatusCode> {
-    let result = sqlx::query!("DELETE FROM branding_assets WHERE id = $1", asset_id)
-        .execute(&*state.db_pool)
+    let tenant_id = tenant_from_headers(&headers)?;
+    state.theme_service.delete_asset(&tenant_id, asset_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Transactional email handlers
+fn system_email(template: &str) -> WhiteLabelResult<SystemEmail> {
+    SystemEmail::parse(template)
+        .ok_or_else(|| WhiteLabelError::NotFound(format!("Unknown system email {}", template)))
+}
+
+#[derive(Debug, Deserialize)]
+pub struct EmailTemplateLocaleQuery {
+    pub locale: Option<String>,
+}
+
+pub async fn list_email_templates(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> WhiteLabelResult<Json<Vec<EmailTemplateSummary>>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Json(state.transactional_email.list_templates(&tenant_id).await?))
+}
+
+/// The platform's template, as a starting point for an override
+pub async fn get_default_email_template(Path(template): Path<String>) -> WhiteLabelResult<Json<EmailTemplateContent>> {
+    Ok(Json(default_template(system_email(&template)?)))
+}
+
+pub async fn save_email_template(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(template): Path<String>,
+    Json(request): Json<SaveEmailTemplateRequest>,
+) -> WhiteLabelResult<Json<EmailTemplateOverride>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let email = system_email(&template)?;
+    let user_id = user_from_headers(&headers);
+
+    Ok(Json(
+        state
+            .transactional_email
+            .save_template(&tenant_id, user_id.as_deref(), email, request)
+            .await?,
+    ))
+}
+
+pub async fn delete_email_template(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(template): Path<String>,
+    Query(query): Query<EmailTemplateLocaleQuery>,
+) -> WhiteLabelResult<StatusCode> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let email = system_email(&template)?;
+    state
+        .transactional_email
+        .delete_template(&tenant_id, email, query.locale.as_deref())
         .await?;
 
-    if result.rows_affected() == 0 {
-        return Err(WhiteLabelError::NotFound("Asset not found".to_string()));
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn preview_email_template(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(template): Path<String>,
+    Json(request): Json<PreviewEmailTemplateRequest>,
+) -> WhiteLabelResult<Json<RenderedEmail>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let email = system_email(&template)?;
+    Ok(Json(state.transactional_email.preview(&tenant_id, email, request).await?))
+}
+
+pub async fn get_email_settings(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> WhiteLabelResult<Json<TenantEmailSettings>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Json(state.transactional_email.get_settings(&tenant_id).await?))
+}
+
+pub async fn update_email_settings(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<UpdateEmailSettingsRequest>,
+) -> WhiteLabelResult<Json<TenantEmailSettings>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let user_id = user_from_headers(&headers);
+    Ok(Json(
+        state
+            .transactional_email
+            .save_settings(&tenant_id, user_id.as_deref(), request)
+            .await?,
+    ))
+}
+
+pub async fn delete_email_settings(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> WhiteLabelResult<StatusCode> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    state.transactional_email.delete_settings(&tenant_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn send_test_email(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<TestEmailRequest>,
+) -> WhiteLabelResult<Json<serde_json::Value>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let message_id = state.transactional_email.send_test(&tenant_id, &request.to).await?;
+
+    Ok(Json(serde_json::json!({ "sent": true, "message_id": message_id })))
+}
+
+/// Render and deliver a system email on behalf of another service
+pub async fn send_system_email(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<SendEmailRequest>,
+) -> WhiteLabelResult<Json<SendEmailResponse>> {
+    require_internal_token(&state, &headers)?;
+    Ok(Json(state.transactional_email.send(request).await?))
+}
+
+// Versioned theme handlers
+pub async fn list_branding_versions(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> WhiteLabelResult<Json<Vec<BrandingVersion>>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Json(state.theme_service.list_versions(&tenant_id).await?))
+}
+
+pub async fn get_branding_version(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(version_id): Path<Uuid>,
+) -> WhiteLabelResult<Json<BrandingVersion>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Json(state.theme_service.get_version(&tenant_id, version_id).await?))
+}
+
+pub async fn restore_branding_version(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(version_id): Path<Uuid>,
+) -> WhiteLabelResult<Json<BrandingVersion>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let user_id = user_from_headers(&headers);
+
+    let draft = state
+        .theme_service
+        .restore_version(&tenant_id, user_id.as_deref(), version_id)
+        .await?;
+    Ok(Json(draft))
+}
+
+pub async fn get_branding_draft(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> WhiteLabelResult<Json<BrandingVersion>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Json(state.theme_service.get_draft(&tenant_id).await?))
+}
+
+pub async fn save_branding_draft(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(theme): Json<BrandingTheme>,
+) -> WhiteLabelResult<Json<BrandingVersion>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let user_id = user_from_headers(&headers);
+
+    let draft = state
+        .theme_service
+        .save_draft(&tenant_id, user_id.as_deref(), theme)
+        .await?;
+    Ok(Json(draft))
+}
+
+pub async fn discard_branding_draft(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> WhiteLabelResult<StatusCode> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    state.theme_service.discard_draft(&tenant_id).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn publish_branding_draft(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> WhiteLabelResult<Json<BrandingVersion>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let user_id = user_from_headers(&headers);
+
+    let published = state
+        .theme_service
+        .publish_draft(&tenant_id, user_id.as_deref())
+        .await?;
+    Ok(Json(published))
+}
+
+// Public branding handlers
+
+/// The published theme for a domain, loaded by the frontend shell at boot
+pub async fn get_public_branding(
+    State(state): State<AppState>,
+    Path(domain): Path<String>,
+) -> WhiteLabelResult<impl IntoResponse> {
+    let branding = state
+        .theme_service
+        .published_for_domain(&domain)
+        .await?
+        .ok_or_else(|| WhiteLabelError::NotFound("No branding for domain".to_string()))?;
+
+    Ok(([(header::CACHE_CONTROL, "public, max-age=60")], Json(branding)))
+}
+
+/// The login page auth-service serves on the domain
+pub async fn get_public_login_page(
+    State(state): State<AppState>,
+    Path(domain): Path<String>,
+) -> WhiteLabelResult<impl IntoResponse> {
+    let page = state
+        .login_page_service
+        .published_for_domain(&domain)
+        .await?
+        .ok_or_else(|| WhiteLabelError::NotFound("No login page for domain".to_string()))?;
+
+    Ok(([(header::CACHE_CONTROL, "public, max-age=60")], Json(page)))
+}
+
+/// A namespace's strings for a locale on the domain, which the frontend
+/// layers over its bundled translations. Revalidated by ETag.
+pub async fn get_public_translations(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((domain, locale, namespace)): Path<(String, String, String)>,
+) -> WhiteLabelResult<Response> {
+    let (tenant_id, revision) = state
+        .translation_service
+        .revision_for_domain(&domain)
+        .await?
+        .ok_or_else(|| WhiteLabelError::NotFound("No translations for domain".to_string()))?;
+
+    let etag = format!("\"{}\"", revision);
+    let cache_headers = [
+        (header::CACHE_CONTROL, "public, max-age=300".to_string()),
+        (header::ETAG, etag.clone()),
+    ];
+    let unchanged = headers
+        .get(header::IF_NONE_MATCH)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.split(',').any(|tag| tag.trim() == etag));
+    if unchanged {
+        return Ok((StatusCode::NOT_MODIFIED, cache_headers).into_response());
     }
 
+    let resolved = state
+        .translation_service
+        .resolve(&tenant_id, &locale, &namespace)
+        .await?;
+    Ok((cache_headers, Json(resolved)).into_response())
+}
+
+/// Redirect to file-service for a published theme's logo or favicon
+pub async fn get_public_branding_asset(
+    State(state): State<AppState>,
+    Path(asset_id): Path<Uuid>,
+) -> WhiteLabelResult<Redirect> {
+    let url = state.theme_service.published_asset_url(asset_id).await?;
+    Ok(Redirect::temporary(&url))
+}
+
+// Hosted login page handlers
+pub async fn get_login_page(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> WhiteLabelResult<Json<LoginPageSettings>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Json(state.login_page_service.get(&tenant_id).await?))
+}
+
+pub async fn save_login_page_draft(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(config): Json<LoginPageConfig>,
+) -> WhiteLabelResult<Json<LoginPageSettings>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let user_id = user_from_headers(&headers);
+    Ok(Json(
+        state
+            .login_page_service
+            .save_draft(&tenant_id, user_id.as_deref(), config)
+            .await?,
+    ))
+}
+
+pub async fn discard_login_page_draft(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> WhiteLabelResult<StatusCode> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    state.login_page_service.discard_draft(&tenant_id).await?;
+
     Ok(StatusCode::NO_CONTENT)
 }
 
+pub async fn publish_login_page_draft(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> WhiteLabelResult<Json<LoginPageSettings>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let user_id = user_from_headers(&headers);
+    Ok(Json(
+        state
+            .login_page_service
+            .publish(&tenant_id, user_id.as_deref())
+            .await?,
+    ))
+}
+
+pub async fn preview_login_page(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<PreviewLoginPageRequest>,
+) -> WhiteLabelResult<Html<String>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Html(state.login_page_service.preview(&tenant_id, request).await?))
+}
+
+// Translation handlers
+
+pub async fn list_translations(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<TranslationQuery>,
+) -> WhiteLabelResult<Json<Vec<TranslationOverride>>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let translations = state
+        .translation_service
+        .list(&tenant_id, query.locale.as_deref(), query.namespace.as_deref())
+        .await?;
+    Ok(Json(translations))
+}
+
+pub async fn get_translation_settings(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+) -> WhiteLabelResult<Json<TranslationSettings>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Json(state.translation_service.get_settings(&tenant_id).await?))
+}
+
+pub async fn update_translation_settings(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Json(request): Json<UpdateTranslationSettingsRequest>,
+) -> WhiteLabelResult<Json<TranslationSettings>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Json(state.translation_service.save_settings(&tenant_id, request).await?))
+}
+
+/// The namespace's strings as the tenant's users get them in the locale
+pub async fn get_resolved_translations(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((locale, namespace)): Path<(String, String)>,
+) -> WhiteLabelResult<Json<ResolvedTranslations>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    Ok(Json(
+        state.translation_service.resolve(&tenant_id, &locale, &namespace).await?,
+    ))
+}
+
+pub async fn save_translations(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path((locale, namespace)): Path<(String, String)>,
+    Json(request): Json<SaveTranslationsRequest>,
+) -> WhiteLabelResult<Json<ResolvedTranslations>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let user_id = user_from_headers(&headers);
+
+    state
+        .translation_service
+        .save_strings(&tenant_id, &locale, &namespace, request.strings, user_id.as_deref())
+        .await?;
+    Ok(Json(
+        state.translation_service.resolve(&tenant_id, &locale, &namespace).await?,
+    ))
+}
+
+pub async fn delete_locale_translations(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Path(locale): Path<String>,
+) -> WhiteLabelResult<StatusCode> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    state.translation_service.delete_locale(&tenant_id, &locale).await?;
+
+    Ok(StatusCode::NO_CONTENT)
+}
+
+/// Download a locale's strings for a translation agency, as XLIFF 1.2 or JSON
+pub async fn export_translations(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<TranslationExportQuery>,
+) -> WhiteLabelResult<impl IntoResponse> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let document = state.translation_service.export(&tenant_id, &query).await?;
+
+    let (content_type, extension) = match query.format.unwrap_or(TranslationFormat::Xliff) {
+        TranslationFormat::Xliff => ("application/x-xliff+xml", "xlf"),
+        TranslationFormat::Json => ("application/json", "json"),
+    };
+    let disposition = format!(
+        "attachment; filename=\"translations-{}.{}\"",
+        query.locale.trim().to_ascii_lowercase(),
+        extension
+    );
+    Ok((
+        [
+            (header::CONTENT_TYPE, content_type.to_string()),
+            (header::CONTENT_DISPOSITION, disposition),
+        ],
+        document,
+    ))
+}
+
+/// Upload an agency's XLIFF 1.2 or JSON document as the request body
+pub async fn import_translations(
+    State(state): State<AppState>,
+    headers: HeaderMap,
+    Query(query): Query<TranslationImportQuery>,
+    document: String,
+) -> WhiteLabelResult<Json<TranslationImportResult>> {
+    let tenant_id = tenant_from_headers(&headers)?;
+    let user_id = user_from_headers(&headers);
+
+    let result = state
+        .translation_service
+        .import(&tenant_id, &query, &document, user_id.as_deref())
+        .await?;
+    Ok(Json(result))
+}
+
 // Workflow status handlers
 #[derive(Debug, Serialize)]
 pub struct WorkflowStatusResponse {
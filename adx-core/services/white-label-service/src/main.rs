@@ -1,17 +1,11 @@
 use std::sync::Arc;
-use tracing_subscriber::{layer::SubscriberExt, util::SubscriberInitExt};
+use adx_shared::telemetry::init_tracing;
 use white_label_service::{config::WhiteLabelConfig, server};
 
 #[tokio::main]
 async fn main() -> Result<(), Box<dyn std::error::Error>> {
-    // Initialize tracing
-    tracing_subscriber::registry()
-        .with(
-            tracing_subscriber::EnvFilter::try_from_default_env()
-                .unwrap_or_else(|_| "white_label_service=info".into()),
-        )
-        .with(tracing_subscriber::fmt::layer())
-        .init();
+    // Initialize tracing, exporting spans when OTEL_EXPORTER_OTLP_ENDPOINT is set
+    let _telemetry = init_tracing("white-label-service", "white_label_service=info")?;
 
     // Load configuration
     let config = Arc::new(WhiteLabelConfig::default());
@@ -218,4 +218,233 @@ pub struct SupportRoutingConfigModel {
     pub support_contact: serde_json::Value,
     pub hierarchy_level: i32,
     pub created_at: DateTime<Utc>,
-}
\ No newline at end of file
+}
+#[derive(Debug, Clone, FromRow)]
+pub struct BrandingVersionModel {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub version: i32,
+    pub status: String,
+    pub brand_name: String,
+    pub logo_asset_id: Option<Uuid>,
+    pub favicon_asset_id: Option<Uuid>,
+    pub color_scheme: sqlx::types::Json<ColorScheme>,
+    pub typography: sqlx::types::Json<Typography>,
+    pub custom_css: Option<String>,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub published_by: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+impl From<BrandingVersionModel> for BrandingVersion {
+    fn from(model: BrandingVersionModel) -> Self {
+        Self {
+            id: model.id,
+            tenant_id: model.tenant_id,
+            version: model.version,
+            status: match model.status.as_str() {
+                "published" => BrandingVersionStatus::Published,
+                "archived" => BrandingVersionStatus::Archived,
+                _ => BrandingVersionStatus::Draft,
+            },
+            theme: BrandingTheme {
+                brand_name: model.brand_name,
+                logo_asset_id: model.logo_asset_id,
+                favicon_asset_id: model.favicon_asset_id,
+                color_scheme: model.color_scheme.0,
+                typography: model.typography.0,
+                custom_css: model.custom_css,
+            },
+            created_by: model.created_by,
+            created_at: model.created_at,
+            updated_at: model.updated_at,
+            published_by: model.published_by,
+            published_at: model.published_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct EmailTemplateModel {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub template_key: String,
+    pub locale: String,
+    pub format: String,
+    pub subject: String,
+    pub body: String,
+    pub text_body: Option<String>,
+    pub enabled: bool,
+    pub updated_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl EmailTemplateModel {
+    /// None for rows of a template the platform no longer has
+    pub fn into_override(self) -> Option<EmailTemplateOverride> {
+        Some(EmailTemplateOverride {
+            id: self.id,
+            template: adx_shared::email::SystemEmail::parse(&self.template_key)?,
+            tenant_id: self.tenant_id,
+            locale: Some(self.locale).filter(|locale| !locale.is_empty()),
+            content: EmailTemplateContent {
+                format: match self.format.as_str() {
+                    "handlebars" => EmailTemplateFormat::Handlebars,
+                    _ => EmailTemplateFormat::Mjml,
+                },
+                subject: self.subject,
+                body: self.body,
+                text_body: self.text_body,
+            },
+            enabled: self.enabled,
+            updated_by: self.updated_by,
+            created_at: self.created_at,
+            updated_at: self.updated_at,
+        })
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct TenantEmailSettingsModel {
+    pub tenant_id: String,
+    pub provider: String,
+    pub from_email: String,
+    pub from_name: Option<String>,
+    pub reply_to: Option<String>,
+    pub smtp_host: Option<String>,
+    pub smtp_port: Option<i32>,
+    pub smtp_username: Option<String>,
+    pub smtp_password_encrypted: Option<String>,
+    pub smtp_starttls: bool,
+    pub ses_region: Option<String>,
+    pub ses_access_key_id: Option<String>,
+    pub ses_secret_access_key_encrypted: Option<String>,
+    pub ses_configuration_set: Option<String>,
+    pub updated_by: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl TenantEmailSettingsModel {
+    pub fn provider(&self) -> EmailProvider {
+        match self.provider.as_str() {
+            "ses" => EmailProvider::Ses,
+            _ => EmailProvider::Smtp,
+        }
+    }
+}
+
+impl From<TenantEmailSettingsModel> for TenantEmailSettings {
+    fn from(model: TenantEmailSettingsModel) -> Self {
+        Self {
+            provider: model.provider(),
+            has_secret: model.smtp_password_encrypted.is_some() || model.ses_secret_access_key_encrypted.is_some(),
+            tenant_id: model.tenant_id,
+            from_email: model.from_email,
+            from_name: model.from_name,
+            reply_to: model.reply_to,
+            smtp_host: model.smtp_host,
+            smtp_port: model.smtp_port.map(|port| port as u16),
+            smtp_username: model.smtp_username,
+            smtp_starttls: model.smtp_starttls,
+            ses_region: model.ses_region,
+            ses_access_key_id: model.ses_access_key_id,
+            ses_configuration_set: model.ses_configuration_set,
+            updated_by: model.updated_by,
+            updated_at: model.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct LoginPageConfigModel {
+    pub tenant_id: String,
+    pub draft: Option<sqlx::types::Json<adx_shared::login_page::LoginPageConfig>>,
+    pub draft_updated_by: Option<String>,
+    pub draft_updated_at: Option<DateTime<Utc>>,
+    pub published: Option<sqlx::types::Json<adx_shared::login_page::LoginPageConfig>>,
+    pub version: i32,
+    pub published_by: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+impl From<LoginPageConfigModel> for LoginPageSettings {
+    fn from(model: LoginPageConfigModel) -> Self {
+        Self {
+            tenant_id: model.tenant_id,
+            draft: model.draft.map(|draft| draft.0),
+            draft_updated_by: model.draft_updated_by,
+            draft_updated_at: model.draft_updated_at,
+            published: model.published.map(|published| published.0),
+            version: model.version,
+            published_by: model.published_by,
+            published_at: model.published_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct ResellerTenantModel {
+    pub reseller_id: Uuid,
+    pub tenant_id: String,
+    pub added_by: Option<String>,
+    pub added_at: DateTime<Utc>,
+}
+
+impl From<ResellerTenantModel> for ResellerTenant {
+    fn from(model: ResellerTenantModel) -> Self {
+        Self {
+            reseller_id: model.reseller_id,
+            tenant_id: model.tenant_id,
+            added_by: model.added_by,
+            added_at: model.added_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct TranslationOverrideModel {
+    pub tenant_id: String,
+    pub locale: String,
+    pub namespace: String,
+    pub message_key: String,
+    pub value: String,
+    pub updated_by: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<TranslationOverrideModel> for TranslationOverride {
+    fn from(model: TranslationOverrideModel) -> Self {
+        Self {
+            locale: model.locale,
+            namespace: model.namespace,
+            key: model.message_key,
+            value: model.value,
+            updated_by: model.updated_by,
+            updated_at: model.updated_at,
+        }
+    }
+}
+
+#[derive(Debug, Clone, FromRow)]
+pub struct TranslationSettingsModel {
+    pub tenant_id: String,
+    pub default_locale: String,
+    pub fallbacks: sqlx::types::Json<std::collections::HashMap<String, Vec<String>>>,
+    pub revision: i64,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl From<TranslationSettingsModel> for TranslationSettings {
+    fn from(model: TranslationSettingsModel) -> Self {
+        Self {
+            tenant_id: model.tenant_id,
+            default_locale: model.default_locale,
+            fallbacks: model.fallbacks.0,
+            revision: model.revision,
+            updated_at: Some(model.updated_at),
+        }
+    }
+}
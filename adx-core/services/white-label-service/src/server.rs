@@ -1,5 +1,12 @@
 use crate::config::WhiteLabelConfig;
-use crate::handlers::{create_routes, AppState};
+use crate::handlers::{create_public_routes, create_routes, AppState};
+use crate::services::{
+    AssetService, DnsService, FileServiceClient, LicenseServiceClient, LoginPageService, ResellerService,
+    ThemeService, TransactionalEmailService, TranslationService,
+};
+use crate::workflows::CertificateRenewalRequest;
+use adx_shared::database::encryption::FieldCipher;
+use adx_shared::telemetry::trace_http_requests;
 use axum::{
     http::{
         header::{ACCEPT, AUTHORIZATION, CONTENT_TYPE},
@@ -37,11 +44,34 @@ impl WhiteLabelServer {
     }
 
     pub async fn start(&self) -> Result<(), Box<dyn std::error::Error>> {
+        let cipher = Arc::new(FieldCipher::from_env()?);
+        let theme_service = self.theme_service();
+        let translation_service = Arc::new(TranslationService::new(self.db_pool.clone()));
         let app_state = AppState {
             db_pool: self.db_pool.clone(),
             temporal_client: self.temporal_client.clone(),
+            config: self.config.clone(),
+            dns_service: Arc::new(DnsService::new(self.config.clone())?),
+            cipher: cipher.clone(),
+            theme_service: theme_service.clone(),
+            transactional_email: Arc::new(TransactionalEmailService::new(
+                self.db_pool.clone(),
+                cipher,
+                self.config.clone(),
+                theme_service.clone(),
+                translation_service.clone(),
+            )),
+            login_page_service: Arc::new(LoginPageService::new(self.db_pool.clone(), theme_service.clone())),
+            reseller_service: Arc::new(ResellerService::new(
+                self.db_pool.clone(),
+                theme_service,
+                Arc::new(LicenseServiceClient::new(&self.config.license_service_url)),
+            )),
+            translation_service,
         };
 
+        self.start_certificate_renewal().await;
+
         let app = self.create_app(app_state);
 
         let addr = format!("0.0.0.0:{}", self.config.server_port);
@@ -53,6 +83,40 @@ impl WhiteLabelServer {
         Ok(())
     }
 
+    /// Start the long-running renewal workflow. Its fixed id keeps a single
+    /// instance running across restarts and replicas.
+    async fn start_certificate_renewal(&self) {
+        if !self.config.ssl_config.auto_renewal {
+            return;
+        }
+
+        let request = CertificateRenewalRequest {
+            renewal_days_before_expiry: self.config.ssl_config.renewal_days_before_expiry,
+            batch_size: self.config.ssl_config.renewal_batch_size,
+        };
+        if let Err(e) = self
+            .temporal_client
+            .start_workflow(
+                "certificate_renewal_workflow",
+                "white-label-certificate-renewal".to_string(),
+                "white-label-task-queue",
+                request,
+            )
+            .await
+        {
+            // Already running from another replica or an earlier start
+            tracing::debug!("Certificate renewal workflow not started: {}", e);
+        }
+    }
+
+    fn theme_service(&self) -> Arc<ThemeService> {
+        Arc::new(ThemeService::new(
+            self.db_pool.clone(),
+            Arc::new(AssetService::new(self.config.clone())),
+            Arc::new(FileServiceClient::new(&self.config.file_service_url)),
+        ))
+    }
+
     fn create_app(&self, state: AppState) -> Router {
         let cors = CorsLayer::new()
             .allow_origin("http://localhost:3000".parse::<HeaderValue>().unwrap())
@@ -68,8 +132,10 @@ impl WhiteLabelServer {
 
         Router::new()
             .nest("/api/v1/white-label", create_routes())
+            .nest("/api/v1/branding", create_public_routes())
             .layer(
                 ServiceBuilder::new()
+                    .layer(axum::middleware::from_fn(trace_http_requests))
                     .layer(cors)
                     .layer(trace_layer)
                     .into_inner(),
@@ -115,9 +181,30 @@ mod tests {
         let temporal_client = Arc::new(create_mock_temporal_client());
         
         let server = WhiteLabelServer::new(config, db_pool, temporal_client);
+        let cipher = Arc::new(FieldCipher::from_env().unwrap());
+        let theme_service = server.theme_service();
+        let translation_service = Arc::new(TranslationService::new(server.db_pool.clone()));
         let app_state = AppState {
             db_pool: server.db_pool.clone(),
             temporal_client: server.temporal_client.clone(),
+            config: server.config.clone(),
+            dns_service: Arc::new(DnsService::new(server.config.clone()).unwrap()),
+            cipher: cipher.clone(),
+            theme_service: theme_service.clone(),
+            transactional_email: Arc::new(TransactionalEmailService::new(
+                server.db_pool.clone(),
+                cipher,
+                server.config.clone(),
+                theme_service.clone(),
+                translation_service.clone(),
+            )),
+            login_page_service: Arc::new(LoginPageService::new(server.db_pool.clone(), theme_service.clone())),
+            reseller_service: Arc::new(ResellerService::new(
+                server.db_pool.clone(),
+                theme_service,
+                Arc::new(LicenseServiceClient::new(&server.config.license_service_url)),
+            )),
+            translation_service,
         };
         
         let app = server.create_app(app_state);
@@ -1,11 +1,27 @@
 pub mod asset_service;
 pub mod dns_service;
 pub mod email_service;
+pub mod email_templates;
+pub mod email_transport;
+pub mod file_service;
+pub mod license_client;
+pub mod login_page_service;
+pub mod reseller_service;
 pub mod ssl_service;
 pub mod storage_service;
+pub mod theme_service;
+pub mod transactional_email;
+pub mod translation_service;
 
 pub use asset_service::AssetService;
 pub use dns_service::DnsService;
 pub use email_service::EmailService;
+pub use file_service::FileServiceClient;
+pub use license_client::LicenseServiceClient;
+pub use login_page_service::LoginPageService;
+pub use reseller_service::ResellerService;
 pub use ssl_service::SslService;
-pub use storage_service::StorageService;
\ No newline at end of file
+pub use storage_service::StorageService;
+pub use theme_service::ThemeService;
+pub use transactional_email::TransactionalEmailService;
+pub use translation_service::TranslationService;
\ No newline at end of file
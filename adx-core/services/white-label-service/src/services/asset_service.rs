@@ -7,6 +7,14 @@ use std::io::Cursor;
 use std::sync::Arc;
 use uuid::Uuid;
 
+/// An upload validated and optimized for its asset type, ready to store
+pub struct PreparedAsset {
+    pub data: Vec<u8>,
+    pub mime_type: String,
+    pub dimensions: Option<AssetDimensions>,
+    pub checksum: String,
+}
+
 pub struct AssetService {
     config: Arc<WhiteLabelConfig>,
 }
@@ -23,6 +31,39 @@ impl AssetService {
         file_data: &[u8],
         filename: &str,
     ) -> WhiteLabelResult<BrandingAsset> {
+        let prepared = self.prepare_upload(&asset_type, file_data)?;
+
+        // Generate file path
+        let asset_id = Uuid::new_v4();
+        let file_extension = self.get_file_extension(&prepared.mime_type);
+        let file_path = format!(
+            "assets/{}/{}/{}.{}",
+            tenant_id,
+            self.get_asset_type_folder(&asset_type),
+            asset_id,
+            file_extension
+        );
+
+        // Store the file (this would use the storage service)
+        // For now, we'll just log the operation
+        tracing::info!("Storing asset at path: {}", file_path);
+
+        Ok(BrandingAsset {
+            id: asset_id,
+            tenant_id: tenant_id.to_string(),
+            asset_type,
+            original_filename: filename.to_string(),
+            file_path,
+            file_size: prepared.data.len() as u64,
+            mime_type: prepared.mime_type,
+            dimensions: prepared.dimensions,
+            checksum: prepared.checksum,
+            created_at: Utc::now(),
+        })
+    }
+
+    /// Validate an upload and optimize it for its asset type
+    pub fn prepare_upload(&self, asset_type: &AssetType, file_data: &[u8]) -> WhiteLabelResult<PreparedAsset> {
         // Validate file size
         if file_data.len() > (self.config.asset_config.max_file_size_mb * 1024 * 1024) as usize {
             return Err(WhiteLabelError::AssetProcessing(
@@ -42,41 +83,19 @@ impl AssetService {
         }
 
         // Process image if it's an image file
-        let (processed_data, dimensions) = if mime_type.starts_with("image/") {
-            self.process_image(file_data, &asset_type)?
+        let (data, dimensions) = if mime_type.starts_with("image/") {
+            self.process_image(file_data, asset_type)?
         } else {
             (file_data.to_vec(), None)
         };
 
-        // Generate file path
-        let asset_id = Uuid::new_v4();
-        let file_extension = self.get_file_extension(&mime_type);
-        let file_path = format!(
-            "assets/{}/{}/{}.{}",
-            tenant_id,
-            self.get_asset_type_folder(&asset_type),
-            asset_id,
-            file_extension
-        );
-
-        // Calculate checksum
-        let checksum = self.calculate_checksum(&processed_data);
-
-        // Store the file (this would use the storage service)
-        // For now, we'll just log the operation
-        tracing::info!("Storing asset at path: {}", file_path);
+        let checksum = self.calculate_checksum(&data);
 
-        Ok(BrandingAsset {
-            id: asset_id,
-            tenant_id: tenant_id.to_string(),
-            asset_type,
-            original_filename: filename.to_string(),
-            file_path,
-            file_size: processed_data.len() as u64,
+        Ok(PreparedAsset {
+            data,
             mime_type,
             dimensions,
             checksum,
-            created_at: Utc::now(),
         })
     }
 
@@ -4,6 +4,25 @@ use crate::types::{DnsRecord, DomainVerificationResult};
 use std::sync::Arc;
 use trust_dns_resolver::{Resolver, config::*};
 
+/// Records a tenant creates to onboard a domain: a TXT record proving
+/// ownership and a CNAME pointing the domain at the platform edge
+pub fn onboarding_records(domain: &str, verification_token: &str, cname_target: &str) -> Vec<DnsRecord> {
+    vec![
+        DnsRecord {
+            record_type: "TXT".to_string(),
+            name: format!("_adx-verification.{}", domain),
+            value: verification_token.to_string(),
+            ttl: 300,
+        },
+        DnsRecord {
+            record_type: "CNAME".to_string(),
+            name: domain.to_string(),
+            value: cname_target.to_string(),
+            ttl: 300,
+        },
+    ]
+}
+
 pub struct DnsService {
     config: Arc<WhiteLabelConfig>,
     resolver: Resolver,
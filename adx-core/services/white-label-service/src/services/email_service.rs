@@ -355,7 +355,7 @@ impl EmailService {
 }
 
 // Handlebars helpers
-fn uppercase_helper(
+pub(crate) fn uppercase_helper(
     h: &handlebars::Helper,
     _: &Handlebars,
     _: &handlebars::Context,
@@ -367,7 +367,7 @@ fn uppercase_helper(
     Ok(())
 }
 
-fn lowercase_helper(
+pub(crate) fn lowercase_helper(
     h: &handlebars::Helper,
     _: &Handlebars,
     _: &handlebars::Context,
@@ -379,7 +379,7 @@ fn lowercase_helper(
     Ok(())
 }
 
-fn format_date_helper(
+pub(crate) fn format_date_helper(
     h: &handlebars::Helper,
     _: &Handlebars,
     _: &handlebars::Context,
@@ -0,0 +1,293 @@
+use crate::error::{WhiteLabelError, WhiteLabelResult};
+use crate::services::email_service::{format_date_helper, lowercase_helper, uppercase_helper};
+use crate::types::*;
+use adx_shared::email::SystemEmail;
+use handlebars::Handlebars;
+use serde_json::{Map, Value};
+
+/// Largest subject and body a tenant can save
+const MAX_SUBJECT_LENGTH: usize = 255;
+const MAX_BODY_BYTES: usize = 100 * 1024;
+
+/// Branded page MJML bodies are placed in unless they are a whole `<mjml>`
+/// document. `content` is the rendered body.
+const LAYOUT: &str = r##"<mjml>
+  <mj-head>
+    <mj-title>{{subject}}</mj-title>
+    <mj-attributes>
+      <mj-all font-family="{{font_family}}" />
+      <mj-text color="{{text_color}}" font-size="15px" line-height="1.6" />
+      <mj-button background-color="{{primary_color}}" color="#ffffff" border-radius="4px" />
+    </mj-attributes>
+  </mj-head>
+  <mj-body background-color="{{background_color}}">
+    <mj-section padding="24px 0 8px">
+      <mj-column>
+        {{#if logo_url}}
+        <mj-image src="{{logo_url}}" alt="{{brand_name}}" width="160px" align="left" />
+        {{else}}
+        <mj-text font-size="22px" font-weight="bold" color="{{primary_color}}">{{brand_name}}</mj-text>
+        {{/if}}
+      </mj-column>
+    </mj-section>
+    <mj-section background-color="#ffffff" border-radius="6px" padding="16px">
+      <mj-column>
+        {{{content}}}
+      </mj-column>
+    </mj-section>
+    <mj-section>
+      <mj-column>
+        <mj-text font-size="12px" color="#8a8a8a" align="center">{{brand_name}}</mj-text>
+      </mj-column>
+    </mj-section>
+  </mj-body>
+</mjml>"##;
+
+/// Renders system emails from Handlebars and MJML templates
+pub struct EmailTemplateEngine {
+    html: Handlebars<'static>,
+    plain: Handlebars<'static>,
+}
+
+impl Default for EmailTemplateEngine {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl EmailTemplateEngine {
+    pub fn new() -> Self {
+        let mut html = Handlebars::new();
+        let mut plain = Handlebars::new();
+        plain.register_escape_fn(handlebars::no_escape);
+
+        for registry in [&mut html, &mut plain] {
+            registry.register_helper("upper", Box::new(uppercase_helper));
+            registry.register_helper("lower", Box::new(lowercase_helper));
+            registry.register_helper("format_date", Box::new(format_date_helper));
+        }
+
+        Self { html, plain }
+    }
+
+    /// Render `content` with `context`, which holds the request's variables
+    /// and the tenant's branding. `fallback_text` is used when the template
+    /// has no text body of its own.
+    pub fn render(
+        &self,
+        content: &EmailTemplateContent,
+        fallback_text: Option<&str>,
+        context: &Map<String, Value>,
+    ) -> WhiteLabelResult<RenderedEmail> {
+        let subject = self
+            .plain
+            .render_template(&content.subject, context)
+            .map_err(|e| WhiteLabelError::TemplateProcessing(format!("Subject template error: {}", e)))?;
+        // Line breaks in a subject would end the header
+        let subject = subject.split_whitespace().collect::<Vec<_>>().join(" ");
+
+        let body = self
+            .html
+            .render_template(&content.body, context)
+            .map_err(|e| WhiteLabelError::TemplateProcessing(format!("Body template error: {}", e)))?;
+
+        let html_body = match content.format {
+            EmailTemplateFormat::Handlebars => body,
+            EmailTemplateFormat::Mjml => {
+                let document = if body.trim_start().starts_with("<mjml") {
+                    body
+                } else {
+                    let mut layout_context = context.clone();
+                    layout_context.insert("subject".to_string(), Value::String(subject.clone()));
+                    layout_context.insert("content".to_string(), Value::String(body));
+                    self.html
+                        .render_template(LAYOUT, &layout_context)
+                        .map_err(|e| WhiteLabelError::TemplateProcessing(format!("Layout error: {}", e)))?
+                };
+                compile_mjml(&document)?
+            }
+        };
+
+        let text_template = content.text_body.as_deref().or(fallback_text).unwrap_or_default();
+        let text_body = self
+            .plain
+            .render_template(text_template, context)
+            .map_err(|e| WhiteLabelError::TemplateProcessing(format!("Text template error: {}", e)))?;
+
+        Ok(RenderedEmail {
+            subject,
+            html_body,
+            text_body: text_body.trim().to_string(),
+        })
+    }
+
+    /// Check a tenant's template before it is saved by rendering it with
+    /// placeholder values
+    pub fn validate(&self, email: SystemEmail, content: &EmailTemplateContent) -> WhiteLabelResult<()> {
+        let subject = content.subject.trim();
+        if subject.is_empty() || subject.len() > MAX_SUBJECT_LENGTH {
+            return Err(WhiteLabelError::Validation(format!(
+                "Subject must be 1 to {} characters",
+                MAX_SUBJECT_LENGTH
+            )));
+        }
+        let body_size = content.body.len() + content.text_body.as_ref().map_or(0, String::len);
+        if content.body.trim().is_empty() || body_size > MAX_BODY_BYTES {
+            return Err(WhiteLabelError::Validation(format!(
+                "Body must be 1 byte to {} KB",
+                MAX_BODY_BYTES / 1024
+            )));
+        }
+
+        let context = sample_context(email, &Map::new(), &default_branding_context());
+        self.render(content, Some(""), &context).map(|_| ())
+    }
+}
+
+/// MJML to responsive HTML
+fn compile_mjml(document: &str) -> WhiteLabelResult<String> {
+    let root = mrml::parse(document).map_err(|e| WhiteLabelError::TemplateProcessing(format!("Invalid MJML: {}", e)))?;
+    root.render(&mrml::prelude::render::RenderOptions::default())
+        .map_err(|e| WhiteLabelError::TemplateProcessing(format!("MJML rendering failed: {}", e)))
+}
+
+/// Branding variables for tenants without a published theme
+pub fn default_branding_context() -> Map<String, Value> {
+    let colors = ColorScheme {
+        primary_color: "#3498db".to_string(),
+        secondary_color: "#2c3e50".to_string(),
+        accent_color: "#e74c3c".to_string(),
+        background_color: "#f4f5f7".to_string(),
+        text_color: "#333333".to_string(),
+    };
+    branding_context("ADX Core", &colors, "Helvetica, Arial, sans-serif", None)
+}
+
+/// Branding variables every template is rendered with
+pub fn branding_context(
+    brand_name: &str,
+    colors: &ColorScheme,
+    font_family: &str,
+    logo_url: Option<String>,
+) -> Map<String, Value> {
+    let mut context = Map::new();
+    context.insert("brand_name".to_string(), brand_name.into());
+    context.insert("primary_color".to_string(), colors.primary_color.clone().into());
+    context.insert("secondary_color".to_string(), colors.secondary_color.clone().into());
+    context.insert("accent_color".to_string(), colors.accent_color.clone().into());
+    context.insert("background_color".to_string(), colors.background_color.clone().into());
+    context.insert("text_color".to_string(), colors.text_color.clone().into());
+    context.insert("font_family".to_string(), font_family.into());
+    context.insert("logo_url".to_string(), logo_url.map_or(Value::Null, Value::String));
+    context
+}
+
+/// The request's variables with the branding on top; branding names cannot
+/// be overridden by a caller
+pub fn render_context(variables: &Map<String, Value>, branding: &Map<String, Value>) -> Map<String, Value> {
+    let mut context = variables.clone();
+    context.extend(branding.clone());
+    context
+}
+
+/// `render_context` with `[name]` for each of the email's variables the
+/// caller did not give, for previews
+pub fn sample_context(
+    email: SystemEmail,
+    variables: &Map<String, Value>,
+    branding: &Map<String, Value>,
+) -> Map<String, Value> {
+    let mut sample = variables.clone();
+    for name in email.variables() {
+        sample
+            .entry(name.to_string())
+            .or_insert_with(|| Value::String(format!("[{}]", name)));
+    }
+    render_context(&sample, branding)
+}
+
+/// The platform's template for a system email
+pub fn default_template(email: SystemEmail) -> EmailTemplateContent {
+    let (subject, body, text_body) = match email {
+        SystemEmail::EmailVerification => (
+            "Verify your email for {{brand_name}}",
+            r#"<mj-text font-size="20px" font-weight="bold">Confirm your email address</mj-text>
+<mj-text>Hello {{user_name}},</mj-text>
+<mj-text>Thanks for signing up for {{brand_name}}. Confirm your email address to finish setting up your account.</mj-text>
+<mj-button href="{{verification_url}}">Verify email</mj-button>
+<mj-text font-size="13px">This link expires in {{expires_in_hours}} hours. If you did not create an account, you can ignore this email.</mj-text>"#,
+            "Hello {{user_name}},\n\n\
+             Thanks for signing up for {{brand_name}}. Confirm your email address to finish setting up your account:\n\n\
+             {{verification_url}}\n\n\
+             This link expires in {{expires_in_hours}} hours. If you did not create an account, you can ignore this email.",
+        ),
+        SystemEmail::PasswordReset => (
+            "Reset your {{brand_name}} password",
+            r#"<mj-text font-size="20px" font-weight="bold">Reset your password</mj-text>
+<mj-text>Hello {{user_name}},</mj-text>
+<mj-text>We received a request to reset your password. Choose a new one with the button below.</mj-text>
+<mj-button href="{{reset_url}}">Reset password</mj-button>
+<mj-text font-size="13px">This link expires in {{expires_in_minutes}} minutes. If you did not ask to reset your password, you can ignore this email.</mj-text>"#,
+            "Hello {{user_name}},\n\n\
+             We received a request to reset your password. Choose a new one here:\n\n\
+             {{reset_url}}\n\n\
+             This link expires in {{expires_in_minutes}} minutes. If you did not ask to reset your password, you can ignore this email.",
+        ),
+        SystemEmail::UserInvitation => (
+            "{{inviter_name}} invited you to {{tenant_name}}",
+            r#"<mj-text font-size="20px" font-weight="bold">You're invited</mj-text>
+<mj-text>{{inviter_name}} invited you to join {{tenant_name}} on {{brand_name}} as {{role}}.</mj-text>
+<mj-button href="{{invitation_url}}">Accept invitation</mj-button>"#,
+            "{{inviter_name}} invited you to join {{tenant_name}} on {{brand_name}} as {{role}}.\n\n\
+             Accept the invitation here:\n\n\
+             {{invitation_url}}",
+        ),
+        SystemEmail::PaymentFailed => (
+            "Your payment failed",
+            r#"<mj-text font-size="20px" font-weight="bold">We could not collect your payment</mj-text>
+<mj-text>Your payment of {{amount}} {{currency}} failed. We will retry the charge on {{next_attempt_date}}.</mj-text>
+<mj-text>Please check your payment method to keep full access to your account.</mj-text>"#,
+            "Your payment of {{amount}} {{currency}} failed. We will retry the charge on {{next_attempt_date}}.\n\n\
+             Please check your payment method to keep full access to your account.",
+        ),
+        SystemEmail::PaymentOutstanding => (
+            "Your payment is still outstanding",
+            r#"<mj-text font-size="20px" font-weight="bold">Your payment is still outstanding</mj-text>
+<mj-text>We could not collect your payment of {{amount}} {{currency}} after {{attempt_count}} attempts.</mj-text>
+<mj-text>Until it is paid these features are unavailable: {{restricted_features}}.</mj-text>
+<mj-text>We will retry the charge on {{next_attempt_date}}.</mj-text>"#,
+            "We could not collect your payment of {{amount}} {{currency}} after {{attempt_count}} attempts.\n\n\
+             Until it is paid these features are unavailable: {{restricted_features}}.\n\n\
+             We will retry the charge on {{next_attempt_date}}.",
+        ),
+        SystemEmail::AccountReadOnly => (
+            "Your account is now read-only",
+            r#"<mj-text font-size="20px" font-weight="bold">Your account is now read-only</mj-text>
+<mj-text>We could not collect your payment of {{amount}} {{currency}} and your account is now read-only.</mj-text>
+<mj-text>Pay the outstanding invoice before {{grace_ends_date}} to restore full access; after that date your account will be suspended.</mj-text>"#,
+            "We could not collect your payment of {{amount}} {{currency}} and your account is now read-only.\n\n\
+             Pay the outstanding invoice before {{grace_ends_date}} to restore full access; after that date your account will be suspended.",
+        ),
+        SystemEmail::AccountSuspended => (
+            "Your account has been suspended",
+            r#"<mj-text font-size="20px" font-weight="bold">Your account has been suspended</mj-text>
+<mj-text>We could not collect your payment of {{amount}} {{currency}} and your account is now suspended.</mj-text>
+<mj-text>Pay the outstanding invoice to restore access.</mj-text>"#,
+            "We could not collect your payment of {{amount}} {{currency}} and your account is now suspended.\n\n\
+             Pay the outstanding invoice to restore access.",
+        ),
+        SystemEmail::PaymentSettled => (
+            "{{#if forgiven}}Your outstanding payment has been waived{{else}}Your payment was received{{/if}}",
+            r#"<mj-text font-size="20px" font-weight="bold">{{#if forgiven}}Your payment has been waived{{else}}Thank you for your payment{{/if}}</mj-text>
+<mj-text>Your invoice of {{amount}} {{currency}} is settled and full access to your account is restored.</mj-text>"#,
+            "Your invoice of {{amount}} {{currency}} is settled and full access to your account is restored.",
+        ),
+    };
+
+    EmailTemplateContent {
+        format: EmailTemplateFormat::Mjml,
+        subject: subject.to_string(),
+        body: body.to_string(),
+        text_body: Some(text_body.to_string()),
+    }
+}
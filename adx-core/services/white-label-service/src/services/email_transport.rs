@@ -0,0 +1,241 @@
+use crate::error::{WhiteLabelError, WhiteLabelResult};
+use crate::types::{EmailProvider, RenderedEmail};
+use base64::Engine;
+use chrono::{DateTime, Utc};
+use hmac::{Hmac, Mac};
+use lettre::message::{Mailbox, MultiPart};
+use lettre::transport::smtp::authentication::Credentials;
+use lettre::{AsyncSmtpTransport, AsyncTransport, Message, Tokio1Executor};
+use serde::Deserialize;
+use sha2::{Digest, Sha256};
+use std::net::IpAddr;
+use std::time::Duration;
+
+/// Ports a tenant's SMTP server may listen on
+const ALLOWED_SMTP_PORTS: [u16; 4] = [25, 465, 587, 2525];
+
+const SMTP_TIMEOUT: Duration = Duration::from_secs(30);
+
+/// An email ready to hand to a transport
+pub struct OutgoingEmail {
+    pub from: Mailbox,
+    pub reply_to: Option<Mailbox>,
+    pub to: Vec<Mailbox>,
+    /// Message-ID header value, without angle brackets
+    pub message_id: String,
+    pub content: RenderedEmail,
+}
+
+impl OutgoingEmail {
+    fn to_message(&self) -> WhiteLabelResult<Message> {
+        let mut builder = Message::builder()
+            .from(self.from.clone())
+            .subject(self.content.subject.clone())
+            .message_id(Some(format!("<{}>", self.message_id)));
+        if let Some(reply_to) = &self.reply_to {
+            builder = builder.reply_to(reply_to.clone());
+        }
+        for to in &self.to {
+            builder = builder.to(to.clone());
+        }
+
+        builder
+            .multipart(MultiPart::alternative_plain_html(
+                self.content.text_body.clone(),
+                self.content.html_body.clone(),
+            ))
+            .map_err(|e| WhiteLabelError::TemplateProcessing(format!("Email building error: {}", e)))
+    }
+}
+
+/// Delivers email through an SMTP server or an SES account
+pub enum EmailTransport {
+    Smtp(AsyncSmtpTransport<Tokio1Executor>),
+    Ses(SesTransport),
+}
+
+impl EmailTransport {
+    /// STARTTLS on `port` when `starttls`, otherwise implicit TLS
+    pub fn smtp(host: &str, port: u16, credentials: Option<(String, String)>, starttls: bool) -> WhiteLabelResult<Self> {
+        let builder = if starttls {
+            AsyncSmtpTransport::<Tokio1Executor>::starttls_relay(host)
+        } else {
+            AsyncSmtpTransport::<Tokio1Executor>::relay(host)
+        }
+        .map_err(|e| WhiteLabelError::Configuration(format!("SMTP configuration error: {}", e)))?;
+
+        let mut builder = builder.port(port).timeout(Some(SMTP_TIMEOUT));
+        if let Some((username, password)) = credentials {
+            builder = builder.credentials(Credentials::new(username, password));
+        }
+        Ok(Self::Smtp(builder.build()))
+    }
+
+    pub fn ses(region: &str, access_key_id: &str, secret_access_key: &str, configuration_set: Option<String>) -> Self {
+        Self::Ses(SesTransport {
+            client: reqwest::Client::new(),
+            region: region.to_string(),
+            access_key_id: access_key_id.to_string(),
+            secret_access_key: secret_access_key.to_string(),
+            configuration_set,
+        })
+    }
+
+    pub fn provider(&self) -> EmailProvider {
+        match self {
+            EmailTransport::Smtp(_) => EmailProvider::Smtp,
+            EmailTransport::Ses(_) => EmailProvider::Ses,
+        }
+    }
+
+    /// Send `email`, returning the provider's id for it
+    pub async fn send(&self, email: &OutgoingEmail) -> WhiteLabelResult<String> {
+        let message = email.to_message()?;
+        match self {
+            EmailTransport::Smtp(transport) => {
+                transport
+                    .send(message)
+                    .await
+                    .map_err(|e| WhiteLabelError::ExternalService(format!("SMTP delivery failed: {}", e)))?;
+                Ok(email.message_id.clone())
+            }
+            EmailTransport::Ses(transport) => transport.send_raw(&message.formatted()).await,
+        }
+    }
+}
+
+/// Checks a tenant's SMTP server before it is saved: a standard mail port
+/// and a host that is not a loopback or private address
+pub fn validate_smtp_server(host: &str, port: u16) -> WhiteLabelResult<()> {
+    if !ALLOWED_SMTP_PORTS.contains(&port) {
+        return Err(WhiteLabelError::Validation(format!(
+            "SMTP port must be one of {:?}",
+            ALLOWED_SMTP_PORTS
+        )));
+    }
+
+    let host = host.trim().trim_end_matches('.').to_ascii_lowercase();
+    if host.is_empty() || host == "localhost" || host.ends_with(".localhost") || host.ends_with(".internal") {
+        return Err(WhiteLabelError::Validation("SMTP host is not allowed".to_string()));
+    }
+    if let Ok(ip) = host.trim_matches(|c| c == '[' || c == ']').parse::<IpAddr>() {
+        let private = match ip {
+            IpAddr::V4(ip) => ip.is_private() || ip.is_loopback() || ip.is_link_local() || ip.is_unspecified(),
+            IpAddr::V6(ip) => ip.is_loopback() || ip.is_unspecified() || (ip.segments()[0] & 0xfe00) == 0xfc00,
+        };
+        if private {
+            return Err(WhiteLabelError::Validation("SMTP host is not allowed".to_string()));
+        }
+    }
+    Ok(())
+}
+
+/// Sends raw MIME messages with the SES v2 API
+pub struct SesTransport {
+    client: reqwest::Client,
+    region: String,
+    access_key_id: String,
+    secret_access_key: String,
+    configuration_set: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct SesSendResponse {
+    message_id: String,
+}
+
+impl SesTransport {
+    async fn send_raw(&self, raw: &[u8]) -> WhiteLabelResult<String> {
+        let host = format!("email.{}.amazonaws.com", self.region);
+        let path = "/v2/email/outbound-emails";
+
+        let mut payload = serde_json::json!({
+            "Content": {
+                "Raw": { "Data": base64::engine::general_purpose::STANDARD.encode(raw) }
+            }
+        });
+        if let Some(configuration_set) = &self.configuration_set {
+            payload["ConfigurationSetName"] = configuration_set.clone().into();
+        }
+        let body = serde_json::to_vec(&payload)
+            .map_err(|e| WhiteLabelError::Internal(format!("SES request encoding failed: {}", e)))?;
+
+        let mut request = self
+            .client
+            .post(format!("https://{}{}", host, path))
+            .header("content-type", "application/json");
+        for (name, value) in self.sign("POST", &host, path, &body, Utc::now()) {
+            request = request.header(name, value);
+        }
+
+        let response = request
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| WhiteLabelError::ExternalService(format!("SES request failed: {}", e)))?;
+
+        if !response.status().is_success() {
+            let status = response.status();
+            let error_text = response.text().await.unwrap_or_default();
+            return Err(WhiteLabelError::ExternalService(format!(
+                "SES delivery failed ({}): {}",
+                status, error_text
+            )));
+        }
+
+        let sent: SesSendResponse = response
+            .json()
+            .await
+            .map_err(|e| WhiteLabelError::ExternalService(format!("Invalid SES response: {}", e)))?;
+        Ok(sent.message_id)
+    }
+
+    /// AWS Signature Version 4 headers for a request without a query string
+    fn sign(&self, method: &str, host: &str, path: &str, body: &[u8], now: DateTime<Utc>) -> Vec<(&'static str, String)> {
+        let amz_date = now.format("%Y%m%dT%H%M%SZ").to_string();
+        let date = now.format("%Y%m%d").to_string();
+
+        let signed_headers = "host;x-amz-date";
+        let canonical_request = format!(
+            "{}\n{}\n\nhost:{}\nx-amz-date:{}\n\n{}\n{}",
+            method,
+            path,
+            host,
+            amz_date,
+            signed_headers,
+            hex::encode(Sha256::digest(body)),
+        );
+
+        let scope = format!("{}/{}/ses/aws4_request", date, self.region);
+        let string_to_sign = format!(
+            "AWS4-HMAC-SHA256\n{}\n{}\n{}",
+            amz_date,
+            scope,
+            hex::encode(Sha256::digest(canonical_request.as_bytes())),
+        );
+
+        let key = hmac_sha256(format!("AWS4{}", self.secret_access_key).as_bytes(), &date);
+        let key = hmac_sha256(&key, &self.region);
+        let key = hmac_sha256(&key, "ses");
+        let key = hmac_sha256(&key, "aws4_request");
+        let signature = hex::encode(hmac_sha256(&key, &string_to_sign));
+
+        vec![
+            ("x-amz-date", amz_date),
+            (
+                "authorization",
+                format!(
+                    "AWS4-HMAC-SHA256 Credential={}/{}, SignedHeaders={}, Signature={}",
+                    self.access_key_id, scope, signed_headers, signature
+                ),
+            ),
+        ]
+    }
+}
+
+fn hmac_sha256(key: &[u8], data: &str) -> Vec<u8> {
+    let mut mac = Hmac::<Sha256>::new_from_slice(key).expect("HMAC takes keys of any length");
+    mac.update(data.as_bytes());
+    mac.finalize().into_bytes().to_vec()
+}
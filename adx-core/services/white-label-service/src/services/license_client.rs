@@ -0,0 +1,68 @@
+use crate::error::{WhiteLabelError, WhiteLabelResult};
+use crate::types::UsageRollupReport;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+#[derive(Debug, Serialize)]
+struct UsageRollupRequest<'a> {
+    tenant_ids: &'a [Uuid],
+    start_date: DateTime<Utc>,
+    end_date: DateTime<Utc>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ApiResponse<T> {
+    data: Option<T>,
+}
+
+/// Reads licensing and billing of reseller customers from license-service
+#[derive(Debug, Clone)]
+pub struct LicenseServiceClient {
+    client: reqwest::Client,
+    base_url: String,
+}
+
+impl LicenseServiceClient {
+    pub fn new(base_url: &str) -> Self {
+        Self {
+            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+        }
+    }
+
+    /// Licenses, billing totals and quota usage of the tenants over a period
+    pub async fn usage_rollup(
+        &self,
+        tenant_ids: &[Uuid],
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+    ) -> WhiteLabelResult<UsageRollupReport> {
+        let response = self
+            .client
+            .post(format!("{}/billing/rollup", self.base_url))
+            .json(&UsageRollupRequest {
+                tenant_ids,
+                start_date,
+                end_date,
+            })
+            .send()
+            .await
+            .map_err(license_service_error)?;
+
+        if !response.status().is_success() {
+            return Err(WhiteLabelError::ExternalService(format!(
+                "License service usage roll-up failed with {}",
+                response.status()
+            )));
+        }
+
+        let body: ApiResponse<UsageRollupReport> = response.json().await.map_err(license_service_error)?;
+        body.data
+            .ok_or_else(|| WhiteLabelError::ExternalService("License service returned no roll-up".to_string()))
+    }
+}
+
+fn license_service_error(error: reqwest::Error) -> WhiteLabelError {
+    WhiteLabelError::ExternalService(format!("License service request failed: {}", error))
+}
@@ -0,0 +1,193 @@
+use crate::error::{WhiteLabelError, WhiteLabelResult};
+use crate::models::LoginPageConfigModel;
+use crate::services::theme_service::public_asset_url;
+use crate::services::ThemeService;
+use crate::types::*;
+use adx_shared::login_page::{
+    render_page, HostedLoginPage, LoginPageBranding, LoginPageConfig, RenderContext,
+};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const CONFIG_COLUMNS: &str =
+    "tenant_id, draft, draft_updated_by, draft_updated_at, published, version, published_by, published_at";
+
+/// Draft, preview and publish hosted login pages. The published page is
+/// what auth-service renders for the tenant's domains.
+pub struct LoginPageService {
+    db_pool: Arc<PgPool>,
+    theme_service: Arc<ThemeService>,
+}
+
+impl LoginPageService {
+    pub fn new(db_pool: Arc<PgPool>, theme_service: Arc<ThemeService>) -> Self {
+        Self { db_pool, theme_service }
+    }
+
+    pub async fn get(&self, tenant_id: &str) -> WhiteLabelResult<LoginPageSettings> {
+        Ok(self.load(tenant_id).await?.map(LoginPageSettings::from).unwrap_or(LoginPageSettings {
+            tenant_id: tenant_id.to_string(),
+            draft: None,
+            draft_updated_by: None,
+            draft_updated_at: None,
+            published: None,
+            version: 0,
+            published_by: None,
+            published_at: None,
+        }))
+    }
+
+    pub async fn save_draft(
+        &self,
+        tenant_id: &str,
+        user_id: Option<&str>,
+        config: LoginPageConfig,
+    ) -> WhiteLabelResult<LoginPageSettings> {
+        config
+            .validate()
+            .map_err(|e| WhiteLabelError::BrandingValidation(e.to_string()))?;
+
+        let saved = sqlx::query_as::<_, LoginPageConfigModel>(&format!(
+            r#"
+            INSERT INTO login_page_configs (tenant_id, draft, draft_updated_by, draft_updated_at)
+            VALUES ($1, $2, $3, NOW())
+            ON CONFLICT (tenant_id) DO UPDATE
+            SET draft = EXCLUDED.draft, draft_updated_by = EXCLUDED.draft_updated_by, draft_updated_at = NOW()
+            RETURNING {}
+            "#,
+            CONFIG_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(sqlx::types::Json(&config))
+        .bind(user_id)
+        .fetch_one(&*self.db_pool)
+        .await?;
+
+        Ok(saved.into())
+    }
+
+    pub async fn discard_draft(&self, tenant_id: &str) -> WhiteLabelResult<()> {
+        let result = sqlx::query!(
+            "UPDATE login_page_configs SET draft = NULL, draft_updated_by = NULL, draft_updated_at = NULL WHERE tenant_id = $1 AND draft IS NOT NULL",
+            tenant_id
+        )
+        .execute(&*self.db_pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(WhiteLabelError::NotFound("No draft login page".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Make the draft the served login page
+    pub async fn publish(&self, tenant_id: &str, user_id: Option<&str>) -> WhiteLabelResult<LoginPageSettings> {
+        let published = sqlx::query_as::<_, LoginPageConfigModel>(&format!(
+            r#"
+            UPDATE login_page_configs
+            SET published = draft, version = version + 1, published_by = $2, published_at = NOW(),
+                draft = NULL, draft_updated_by = NULL, draft_updated_at = NULL
+            WHERE tenant_id = $1 AND draft IS NOT NULL
+            RETURNING {}
+            "#,
+            CONFIG_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_optional(&*self.db_pool)
+        .await?
+        .ok_or_else(|| WhiteLabelError::Conflict("No draft login page to publish".to_string()))?;
+
+        tracing::info!("Published login page version {} for tenant {}", published.version, tenant_id);
+        Ok(published.into())
+    }
+
+    /// Render a login page the way auth-service would serve it, with the
+    /// tenant's published theme. The preview's form does not submit.
+    pub async fn preview(&self, tenant_id: &str, request: PreviewLoginPageRequest) -> WhiteLabelResult<String> {
+        let config = match request.config {
+            Some(config) => {
+                config
+                    .validate()
+                    .map_err(|e| WhiteLabelError::BrandingValidation(e.to_string()))?;
+                config
+            }
+            None => {
+                let settings = self.get(tenant_id).await?;
+                settings.draft.or(settings.published).unwrap_or_default()
+            }
+        };
+
+        let branding = self.branding(tenant_id).await?;
+        let locale = config.resolve_locale(request.locale.as_deref()).to_string();
+        let page = HostedLoginPage {
+            tenant_id: Some(tenant_id.to_string()),
+            version: 0,
+            config,
+            branding,
+        };
+
+        Ok(render_page(
+            &page,
+            request.page,
+            &RenderContext {
+                locale,
+                api_base: "/api/v1/auth".to_string(),
+                redirect_path: None,
+                nonce: Uuid::new_v4().simple().to_string(),
+                preview: true,
+            },
+        ))
+    }
+
+    /// The login page served on a verified custom domain. Tenants that have
+    /// not published one get the default layout in their theme.
+    pub async fn published_for_domain(&self, domain: &str) -> WhiteLabelResult<Option<HostedLoginPage>> {
+        let domain = domain.trim().trim_end_matches('.').to_ascii_lowercase();
+        let tenant_id = sqlx::query_scalar!(
+            "SELECT tenant_id FROM custom_domains WHERE domain = $1 AND status = 'verified'",
+            domain
+        )
+        .fetch_optional(&*self.db_pool)
+        .await?;
+        let Some(tenant_id) = tenant_id else {
+            return Ok(None);
+        };
+
+        let settings = self.get(&tenant_id).await?;
+        Ok(Some(HostedLoginPage {
+            branding: self.branding(&tenant_id).await?,
+            tenant_id: Some(tenant_id),
+            version: settings.version,
+            config: settings.published.unwrap_or_default(),
+        }))
+    }
+
+    async fn branding(&self, tenant_id: &str) -> WhiteLabelResult<LoginPageBranding> {
+        let Some(version) = self.theme_service.published_for_tenant(tenant_id).await? else {
+            return Ok(LoginPageBranding::default());
+        };
+        let theme = version.theme;
+
+        Ok(LoginPageBranding {
+            brand_name: theme.brand_name,
+            logo_url: theme.logo_asset_id.map(public_asset_url),
+            favicon_url: theme.favicon_asset_id.map(public_asset_url),
+            primary_color: theme.color_scheme.primary_color,
+            background_color: theme.color_scheme.background_color,
+            text_color: theme.color_scheme.text_color,
+            font_family: theme.typography.font_family,
+        })
+    }
+
+    async fn load(&self, tenant_id: &str) -> WhiteLabelResult<Option<LoginPageConfigModel>> {
+        Ok(sqlx::query_as::<_, LoginPageConfigModel>(&format!(
+            "SELECT {} FROM login_page_configs WHERE tenant_id = $1",
+            CONFIG_COLUMNS
+        ))
+        .bind(tenant_id)
+        .fetch_optional(&*self.db_pool)
+        .await?)
+    }
+}
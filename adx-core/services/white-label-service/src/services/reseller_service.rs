@@ -0,0 +1,270 @@
+use crate::error::{WhiteLabelError, WhiteLabelResult};
+use crate::models::{ResellerHierarchyModel, ResellerTenantModel};
+use crate::services::license_client::LicenseServiceClient;
+use crate::services::ThemeService;
+use crate::types::*;
+use chrono::{DateTime, Utc};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const RESELLER_COLUMNS: &str = "id, parent_reseller_id, tenant_id, reseller_name, reseller_type, \
+     commission_rate, revenue_share_model, support_contact, branding_overrides, allowed_features, \
+     created_at, updated_at";
+
+/// Reseller `$1` and its sub-resellers. Hierarchies are followed 16 levels
+/// deep at most, guarding against cycles.
+const SUBTREE: &str = r#"
+    WITH RECURSIVE subtree AS (
+        SELECT id, 0 AS depth FROM reseller_hierarchies WHERE id = $1
+        UNION ALL
+        SELECT r.id, s.depth + 1
+        FROM reseller_hierarchies r
+        JOIN subtree s ON r.parent_reseller_id = s.id
+        WHERE s.depth < 16
+    )
+"#;
+
+/// Resellers' customer tenants, and what a reseller's admins may manage: the
+/// customers of the reseller and of its sub-resellers.
+pub struct ResellerService {
+    db_pool: Arc<PgPool>,
+    theme_service: Arc<ThemeService>,
+    license_client: Arc<LicenseServiceClient>,
+}
+
+impl ResellerService {
+    pub fn new(
+        db_pool: Arc<PgPool>,
+        theme_service: Arc<ThemeService>,
+        license_client: Arc<LicenseServiceClient>,
+    ) -> Self {
+        Self {
+            db_pool,
+            theme_service,
+            license_client,
+        }
+    }
+
+    pub async fn get(&self, reseller_id: Uuid) -> WhiteLabelResult<ResellerHierarchy> {
+        sqlx::query_as::<_, ResellerHierarchyModel>(&format!(
+            "SELECT {} FROM reseller_hierarchies WHERE id = $1",
+            RESELLER_COLUMNS
+        ))
+        .bind(reseller_id)
+        .fetch_optional(&*self.db_pool)
+        .await?
+        .map(ResellerHierarchy::from)
+        .ok_or_else(|| WhiteLabelError::NotFound("Reseller not found".to_string()))
+    }
+
+    /// The reseller whose own tenant is the caller's. Tenants that are not
+    /// resellers have no partner access.
+    pub async fn for_tenant(&self, tenant_id: &str) -> WhiteLabelResult<ResellerHierarchy> {
+        sqlx::query_as::<_, ResellerHierarchyModel>(&format!(
+            "SELECT {} FROM reseller_hierarchies WHERE tenant_id = $1",
+            RESELLER_COLUMNS
+        ))
+        .bind(tenant_id)
+        .fetch_optional(&*self.db_pool)
+        .await?
+        .map(ResellerHierarchy::from)
+        .ok_or_else(|| WhiteLabelError::Forbidden("Tenant is not a reseller".to_string()))
+    }
+
+    /// Level of the reseller in its hierarchy; top-level resellers are 1
+    pub async fn hierarchy_level(&self, reseller_id: Uuid) -> WhiteLabelResult<u32> {
+        let level = sqlx::query_scalar::<_, i32>(
+            r#"
+            WITH RECURSIVE ancestors AS (
+                SELECT id, parent_reseller_id, 1 AS level FROM reseller_hierarchies WHERE id = $1
+                UNION ALL
+                SELECT r.id, r.parent_reseller_id, a.level + 1
+                FROM reseller_hierarchies r
+                JOIN ancestors a ON r.id = a.parent_reseller_id
+                WHERE a.level < 16
+            )
+            SELECT COALESCE(MAX(level), 1) FROM ancestors
+            "#,
+        )
+        .bind(reseller_id)
+        .fetch_one(&*self.db_pool)
+        .await?;
+
+        Ok(level as u32)
+    }
+
+    /// Sub-resellers of the reseller at any depth, excluding the reseller
+    pub async fn sub_resellers(&self, reseller_id: Uuid) -> WhiteLabelResult<Vec<ResellerHierarchy>> {
+        let resellers = sqlx::query_as::<_, ResellerHierarchyModel>(&format!(
+            r#"
+            {}
+            SELECT {} FROM reseller_hierarchies
+            WHERE id IN (SELECT id FROM subtree WHERE depth > 0)
+            ORDER BY reseller_name
+            "#,
+            SUBTREE, RESELLER_COLUMNS
+        ))
+        .bind(reseller_id)
+        .fetch_all(&*self.db_pool)
+        .await?;
+
+        Ok(resellers.into_iter().map(ResellerHierarchy::from).collect())
+    }
+
+    /// The reseller's direct customers
+    pub async fn list_tenants(&self, reseller_id: Uuid) -> WhiteLabelResult<Vec<ResellerTenant>> {
+        let tenants = sqlx::query_as::<_, ResellerTenantModel>(
+            "SELECT reseller_id, tenant_id, added_by, added_at FROM reseller_tenants WHERE reseller_id = $1 ORDER BY added_at",
+        )
+        .bind(reseller_id)
+        .fetch_all(&*self.db_pool)
+        .await?;
+
+        Ok(tenants.into_iter().map(ResellerTenant::from).collect())
+    }
+
+    /// Customers of the reseller and of its sub-resellers
+    pub async fn managed_tenants(&self, reseller_id: Uuid) -> WhiteLabelResult<Vec<ResellerTenant>> {
+        let tenants = sqlx::query_as::<_, ResellerTenantModel>(&format!(
+            r#"
+            {}
+            SELECT reseller_id, tenant_id, added_by, added_at FROM reseller_tenants
+            WHERE reseller_id IN (SELECT id FROM subtree)
+            ORDER BY added_at
+            "#,
+            SUBTREE
+        ))
+        .bind(reseller_id)
+        .fetch_all(&*self.db_pool)
+        .await?;
+
+        Ok(tenants.into_iter().map(ResellerTenant::from).collect())
+    }
+
+    /// Make the tenant a customer of the reseller. A tenant belongs to one
+    /// reseller; reseller tenants are placed with `parent_reseller_id`.
+    pub async fn assign_tenant(
+        &self,
+        reseller_id: Uuid,
+        tenant_id: &str,
+        added_by: Option<&str>,
+    ) -> WhiteLabelResult<ResellerTenant> {
+        Uuid::parse_str(tenant_id)
+            .map_err(|_| WhiteLabelError::Validation(format!("Invalid tenant id {}", tenant_id)))?;
+        self.get(reseller_id).await?;
+
+        let is_reseller = sqlx::query_scalar!(
+            r#"SELECT EXISTS (SELECT 1 FROM reseller_hierarchies WHERE tenant_id = $1) as "exists!""#,
+            tenant_id
+        )
+        .fetch_one(&*self.db_pool)
+        .await?;
+        if is_reseller {
+            return Err(WhiteLabelError::ResellerHierarchy(format!(
+                "Tenant {} is a reseller; set its parent reseller instead",
+                tenant_id
+            )));
+        }
+
+        let assigned = sqlx::query_as::<_, ResellerTenantModel>(
+            r#"
+            INSERT INTO reseller_tenants (reseller_id, tenant_id, added_by)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (tenant_id) DO NOTHING
+            RETURNING reseller_id, tenant_id, added_by, added_at
+            "#,
+        )
+        .bind(reseller_id)
+        .bind(tenant_id)
+        .bind(added_by)
+        .fetch_optional(&*self.db_pool)
+        .await?
+        .ok_or_else(|| WhiteLabelError::Conflict(format!("Tenant {} already belongs to a reseller", tenant_id)))?;
+
+        tracing::info!("Assigned tenant {} to reseller {}", tenant_id, reseller_id);
+        Ok(assigned.into())
+    }
+
+    pub async fn remove_tenant(&self, reseller_id: Uuid, tenant_id: &str) -> WhiteLabelResult<()> {
+        let result = sqlx::query!(
+            "DELETE FROM reseller_tenants WHERE reseller_id = $1 AND tenant_id = $2",
+            reseller_id,
+            tenant_id
+        )
+        .execute(&*self.db_pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(WhiteLabelError::NotFound("Tenant is not a customer of the reseller".to_string()));
+        }
+
+        tracing::info!("Removed tenant {} from reseller {}", tenant_id, reseller_id);
+        Ok(())
+    }
+
+    /// Fail unless the tenant is a customer of the reseller or of one of
+    /// its sub-resellers
+    pub async fn authorize_customer(&self, reseller_id: Uuid, tenant_id: &str) -> WhiteLabelResult<()> {
+        let managed = sqlx::query_scalar::<_, bool>(&format!(
+            r#"
+            {}
+            SELECT EXISTS (
+                SELECT 1 FROM reseller_tenants
+                WHERE tenant_id = $2 AND reseller_id IN (SELECT id FROM subtree)
+            )
+            "#,
+            SUBTREE
+        ))
+        .bind(reseller_id)
+        .bind(tenant_id)
+        .fetch_one(&*self.db_pool)
+        .await?;
+
+        if !managed {
+            return Err(WhiteLabelError::Forbidden(format!(
+                "Tenant {} is not managed by this reseller",
+                tenant_id
+            )));
+        }
+        Ok(())
+    }
+
+    pub async fn customer_branding(&self, tenant_id: &str) -> WhiteLabelResult<CustomerBranding> {
+        let draft = match self.theme_service.get_draft(tenant_id).await {
+            Ok(draft) => Some(draft),
+            Err(WhiteLabelError::NotFound(_)) => None,
+            Err(e) => return Err(e),
+        };
+        let published = self.theme_service.own_published(tenant_id).await?;
+        let effective = self.theme_service.published_for_tenant(tenant_id).await?;
+        let inherited = effective
+            .as_ref()
+            .is_some_and(|version| version.tenant_id != tenant_id);
+
+        Ok(CustomerBranding {
+            tenant_id: tenant_id.to_string(),
+            draft,
+            published,
+            effective,
+            inherited,
+        })
+    }
+
+    /// Usage and billing rolled up across every tenant the reseller manages
+    pub async fn usage(
+        &self,
+        reseller_id: Uuid,
+        start_date: DateTime<Utc>,
+        end_date: DateTime<Utc>,
+    ) -> WhiteLabelResult<UsageRollupReport> {
+        let tenant_ids: Vec<Uuid> = self
+            .managed_tenants(reseller_id)
+            .await?
+            .iter()
+            .filter_map(|tenant| Uuid::parse_str(&tenant.tenant_id).ok())
+            .collect();
+
+        self.license_client.usage_rollup(&tenant_ids, start_date, end_date).await
+    }
+}
@@ -1,28 +1,64 @@
 use crate::config::WhiteLabelConfig;
 use crate::error::{WhiteLabelError, WhiteLabelResult};
 use crate::types::{SslCertificateResult, SslStatus};
-use chrono::{Duration, Utc};
+use adx_shared::database::encryption::FieldCipher;
+use chrono::{DateTime, Duration, TimeZone, Utc};
+use instant_acme::{
+    Account, AccountCredentials, AuthorizationStatus, ChallengeType, Identifier, NewAccount, NewOrder, Order,
+    OrderStatus,
+};
+use rcgen::{Certificate, CertificateParams, DistinguishedName};
+use sqlx::PgPool;
 use std::sync::Arc;
 use uuid::Uuid;
 
+/// Scope ACME account credentials are encrypted under; they belong to no tenant
+const PLATFORM_KEY_SCOPE: &str = "platform";
+/// How long a challenge is answered for
+const CHALLENGE_TTL_MINUTES: i64 = 30;
+/// Order status checks before giving up on validation
+const ORDER_POLL_ATTEMPTS: u32 = 20;
+
+/// A provisioned certificate. The PEMs are only set for certificates the
+/// platform holds the key for; provider-managed ones terminate elsewhere.
+pub struct ProvisionedCertificate {
+    pub result: SslCertificateResult,
+    pub certificate_pem: Option<String>,
+    pub private_key_pem: Option<String>,
+}
+
+impl From<SslCertificateResult> for ProvisionedCertificate {
+    fn from(result: SslCertificateResult) -> Self {
+        Self {
+            result,
+            certificate_pem: None,
+            private_key_pem: None,
+        }
+    }
+}
+
 pub struct SslService {
     config: Arc<WhiteLabelConfig>,
     client: reqwest::Client,
+    db_pool: Arc<PgPool>,
+    cipher: Arc<FieldCipher>,
 }
 
 impl SslService {
-    pub fn new(config: Arc<WhiteLabelConfig>) -> Self {
+    pub fn new(config: Arc<WhiteLabelConfig>, db_pool: Arc<PgPool>, cipher: Arc<FieldCipher>) -> Self {
         Self {
             config,
             client: reqwest::Client::new(),
+            db_pool,
+            cipher,
         }
     }
 
-    pub async fn provision_certificate(&self, domain: &str) -> WhiteLabelResult<SslCertificateResult> {
+    pub async fn provision_certificate(&self, domain: &str) -> WhiteLabelResult<ProvisionedCertificate> {
         match self.config.ssl_config.provider.as_str() {
             "letsencrypt" => self.provision_letsencrypt_certificate(domain).await,
-            "aws_acm" => self.provision_aws_acm_certificate(domain).await,
-            "cloudflare" => self.provision_cloudflare_certificate(domain).await,
+            "aws_acm" => self.provision_aws_acm_certificate(domain).await.map(Into::into),
+            "cloudflare" => self.provision_cloudflare_certificate(domain).await.map(Into::into),
             _ => Err(WhiteLabelError::SslCertificate(format!(
                 "Unsupported SSL provider: {}",
                 self.config.ssl_config.provider
@@ -30,30 +66,212 @@ impl SslService {
         }
     }
 
+    /// Order a certificate over ACME. Ownership is proved with HTTP-01: the
+    /// challenges are stored for the gateway to answer on the domain, which
+    /// already points at the platform edge.
     async fn provision_letsencrypt_certificate(
         &self,
         domain: &str,
-    ) -> WhiteLabelResult<SslCertificateResult> {
+    ) -> WhiteLabelResult<ProvisionedCertificate> {
         tracing::info!("Provisioning Let's Encrypt certificate for domain: {}", domain);
 
-        // In a real implementation, this would use the ACME protocol
-        // For now, we'll simulate the process
-        
-        let certificate_id = Uuid::new_v4().to_string();
-        let expires_at = Utc::now() + Duration::days(90); // Let's Encrypt certificates are valid for 90 days
+        let account = self.acme_account().await?;
+        let mut order = account
+            .new_order(&NewOrder {
+                identifiers: &[Identifier::Dns(domain.to_string())],
+            })
+            .await
+            .map_err(acme_error)?;
 
-        // Simulate certificate provisioning process
-        tokio::time::sleep(tokio::time::Duration::from_secs(2)).await;
+        let authorizations = order.authorizations().await.map_err(acme_error)?;
+        let mut tokens = Vec::new();
+        for authorization in &authorizations {
+            match authorization.status {
+                AuthorizationStatus::Valid => continue,
+                AuthorizationStatus::Pending => {}
+                status => {
+                    return Err(WhiteLabelError::SslCertificate(format!(
+                        "Authorization for {} is {:?}",
+                        domain, status
+                    )))
+                }
+            }
 
-        Ok(SslCertificateResult {
-            certificate_id,
-            certificate_arn: None,
-            status: SslStatus::Issued,
-            expires_at,
-            auto_renewal: self.config.ssl_config.auto_renewal,
+            let challenge = authorization
+                .challenges
+                .iter()
+                .find(|challenge| challenge.r#type == ChallengeType::Http01)
+                .ok_or_else(|| {
+                    WhiteLabelError::SslCertificate(format!("No HTTP-01 challenge offered for {}", domain))
+                })?;
+            let key_authorization = order.key_authorization(challenge);
+            self.store_challenge(domain, &challenge.token, key_authorization.as_str()).await?;
+            tokens.push(challenge.token.clone());
+
+            order.set_challenge_ready(&challenge.url).await.map_err(acme_error)?;
+        }
+
+        let issued = self.complete_order(&mut order, domain).await;
+        self.clear_challenges(&tokens).await?;
+        let (certificate_pem, private_key_pem) = issued?;
+
+        Ok(ProvisionedCertificate {
+            result: SslCertificateResult {
+                certificate_id: Uuid::new_v4().to_string(),
+                certificate_arn: None,
+                status: SslStatus::Issued,
+                expires_at: certificate_expiry(&certificate_pem)?,
+                auto_renewal: self.config.ssl_config.auto_renewal,
+            },
+            certificate_pem: Some(certificate_pem),
+            private_key_pem: Some(private_key_pem),
         })
     }
 
+    /// Wait for the authority to validate the challenges, then finalize the
+    /// order with a fresh key and download the certificate chain
+    async fn complete_order(&self, order: &mut Order, domain: &str) -> WhiteLabelResult<(String, String)> {
+        let mut delay = std::time::Duration::from_millis(500);
+        let mut attempts = 0;
+        loop {
+            tokio::time::sleep(delay).await;
+            let status = order.refresh().await.map_err(acme_error)?.status;
+            match status {
+                OrderStatus::Ready | OrderStatus::Valid => break,
+                OrderStatus::Invalid => {
+                    return Err(WhiteLabelError::SslCertificate(format!(
+                        "Certificate authority could not validate {}",
+                        domain
+                    )))
+                }
+                OrderStatus::Pending | OrderStatus::Processing => {}
+            }
+
+            attempts += 1;
+            if attempts >= ORDER_POLL_ATTEMPTS {
+                return Err(WhiteLabelError::SslCertificate(format!(
+                    "Timed out waiting for validation of {}",
+                    domain
+                )));
+            }
+            delay = (delay * 2).min(std::time::Duration::from_secs(10));
+        }
+
+        let mut params = CertificateParams::new(vec![domain.to_string()]);
+        params.distinguished_name = DistinguishedName::new();
+        let key = Certificate::from_params(params)
+            .map_err(|e| WhiteLabelError::SslCertificate(format!("Key generation failed: {}", e)))?;
+        let csr = key
+            .serialize_request_der()
+            .map_err(|e| WhiteLabelError::SslCertificate(format!("CSR generation failed: {}", e)))?;
+        order.finalize(&csr).await.map_err(acme_error)?;
+
+        for _ in 0..ORDER_POLL_ATTEMPTS {
+            if let Some(certificate_pem) = order.certificate().await.map_err(acme_error)? {
+                return Ok((certificate_pem, key.serialize_private_key_pem()));
+            }
+            tokio::time::sleep(std::time::Duration::from_secs(1)).await;
+        }
+
+        Err(WhiteLabelError::SslCertificate(format!(
+            "Timed out downloading the certificate for {}",
+            domain
+        )))
+    }
+
+    /// The platform's account with the configured directory, registered on first use
+    async fn acme_account(&self) -> WhiteLabelResult<Account> {
+        let directory_url = &self.config.ssl_config.certificate_authority;
+
+        let existing = sqlx::query!(
+            "SELECT credentials_encrypted FROM acme_accounts WHERE directory_url = $1",
+            directory_url
+        )
+        .fetch_optional(&*self.db_pool)
+        .await?;
+
+        if let Some(existing) = existing {
+            let credentials = self
+                .cipher
+                .decrypt(PLATFORM_KEY_SCOPE, "acme_account", &existing.credentials_encrypted)
+                .await
+                .map_err(|e| WhiteLabelError::Internal(format!("ACME account credentials: {}", e)))?;
+            let credentials: AccountCredentials = serde_json::from_str(&credentials)
+                .map_err(|e| WhiteLabelError::Internal(format!("ACME account credentials: {}", e)))?;
+            return Account::from_credentials(credentials).await.map_err(acme_error);
+        }
+
+        let contact = self
+            .config
+            .ssl_config
+            .contact_email
+            .as_ref()
+            .map(|email| format!("mailto:{}", email));
+        let contact: Vec<&str> = contact.iter().map(String::as_str).collect();
+        let (account, credentials) = Account::create(
+            &NewAccount {
+                contact: &contact,
+                terms_of_service_agreed: true,
+                only_return_existing: false,
+            },
+            directory_url,
+            None,
+        )
+        .await
+        .map_err(acme_error)?;
+
+        let credentials = serde_json::to_string(&credentials)
+            .map_err(|e| WhiteLabelError::Internal(format!("ACME account credentials: {}", e)))?;
+        let credentials_encrypted = self
+            .cipher
+            .encrypt(PLATFORM_KEY_SCOPE, "acme_account", &credentials)
+            .await
+            .map_err(|e| WhiteLabelError::Internal(format!("ACME account credentials: {}", e)))?;
+        sqlx::query!(
+            r#"
+            INSERT INTO acme_accounts (directory_url, credentials_encrypted)
+            VALUES ($1, $2)
+            ON CONFLICT (directory_url) DO NOTHING
+            "#,
+            directory_url,
+            credentials_encrypted
+        )
+        .execute(&*self.db_pool)
+        .await?;
+
+        tracing::info!("Registered ACME account with {}", directory_url);
+        Ok(account)
+    }
+
+    async fn store_challenge(&self, domain: &str, token: &str, key_authorization: &str) -> WhiteLabelResult<()> {
+        sqlx::query!(
+            r#"
+            INSERT INTO acme_challenges (token, domain, key_authorization, expires_at)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (token) DO UPDATE SET key_authorization = EXCLUDED.key_authorization, expires_at = EXCLUDED.expires_at
+            "#,
+            token,
+            domain,
+            key_authorization,
+            Utc::now() + Duration::minutes(CHALLENGE_TTL_MINUTES)
+        )
+        .execute(&*self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn clear_challenges(&self, tokens: &[String]) -> WhiteLabelResult<()> {
+        sqlx::query!(
+            "DELETE FROM acme_challenges WHERE token = ANY($1) OR expires_at < NOW()",
+            tokens
+        )
+        .execute(&*self.db_pool)
+        .await?;
+
+        Ok(())
+    }
+
     async fn provision_aws_acm_certificate(
         &self,
         domain: &str,
@@ -93,19 +311,27 @@ impl SslService {
         })
     }
 
-    pub async fn renew_certificate(&self, certificate_id: &str) -> WhiteLabelResult<SslCertificateResult> {
-        tracing::info!("Renewing SSL certificate: {}", certificate_id);
-
-        // In a real implementation, this would renew the certificate
-        let expires_at = Utc::now() + Duration::days(90);
+    /// Renew a domain's certificate. ACME has no renewal as such: a new
+    /// certificate is ordered with a new key.
+    pub async fn renew_certificate(
+        &self,
+        domain: &str,
+        certificate_id: &str,
+    ) -> WhiteLabelResult<ProvisionedCertificate> {
+        tracing::info!("Renewing SSL certificate {} for domain: {}", certificate_id, domain);
 
-        Ok(SslCertificateResult {
-            certificate_id: certificate_id.to_string(),
-            certificate_arn: None,
-            status: SslStatus::Issued,
-            expires_at,
-            auto_renewal: self.config.ssl_config.auto_renewal,
-        })
+        match self.config.ssl_config.provider.as_str() {
+            "letsencrypt" => self.provision_letsencrypt_certificate(domain).await,
+            // Provider-managed certificates renew themselves
+            _ => Ok(SslCertificateResult {
+                certificate_id: certificate_id.to_string(),
+                certificate_arn: None,
+                status: SslStatus::Issued,
+                expires_at: Utc::now() + Duration::days(90),
+                auto_renewal: true,
+            }
+            .into()),
+        }
     }
 
     pub async fn revoke_certificate(&self, certificate_id: &str) -> WhiteLabelResult<()> {
@@ -153,4 +379,21 @@ impl SslService {
 
         Ok(())
     }
-}
\ No newline at end of file
+}
+
+fn acme_error(error: instant_acme::Error) -> WhiteLabelError {
+    WhiteLabelError::SslCertificate(format!("ACME error: {}", error))
+}
+
+/// When the leaf certificate of a PEM chain expires
+fn certificate_expiry(certificate_pem: &str) -> WhiteLabelResult<DateTime<Utc>> {
+    let (_, pem) = x509_parser::pem::parse_x509_pem(certificate_pem.as_bytes())
+        .map_err(|e| WhiteLabelError::SslCertificate(format!("Invalid certificate PEM: {}", e)))?;
+    let certificate = pem
+        .parse_x509()
+        .map_err(|e| WhiteLabelError::SslCertificate(format!("Invalid certificate: {}", e)))?;
+
+    Utc.timestamp_opt(certificate.validity().not_after.timestamp(), 0)
+        .single()
+        .ok_or_else(|| WhiteLabelError::SslCertificate("Certificate expiry out of range".to_string()))
+}
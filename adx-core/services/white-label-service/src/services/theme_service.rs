@@ -0,0 +1,518 @@
+use crate::error::{WhiteLabelError, WhiteLabelResult};
+use crate::models::{BrandingAssetModel, BrandingVersionModel};
+use crate::services::{AssetService, FileServiceClient};
+use crate::types::*;
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const VERSION_COLUMNS: &str = "id, tenant_id, version, status, brand_name, logo_asset_id, favicon_asset_id, \
+     color_scheme, typography, custom_css, created_by, created_at, updated_at, published_by, published_at";
+
+const ASSET_COLUMNS: &str = "id, tenant_id, asset_type, original_filename, file_path, file_size, mime_type, \
+     dimensions_width, dimensions_height, checksum, created_at";
+
+/// Largest custom stylesheet a theme can carry
+const MAX_CUSTOM_CSS_BYTES: usize = 64 * 1024;
+
+/// Tenants whose published theme tenant `$1` is served, nearest first: the
+/// tenant itself, then the tenant of the reseller owning it (or, for a
+/// reseller's own tenant, of its parent reseller), and so on up the chain
+const BRANDING_CHAIN: &str = r#"
+    WITH RECURSIVE resellers AS (
+        SELECT r.id, r.parent_reseller_id, r.tenant_id, 1 AS depth
+        FROM reseller_hierarchies r
+        WHERE r.id = (SELECT reseller_id FROM reseller_tenants WHERE tenant_id = $1)
+           OR r.id = (SELECT parent_reseller_id FROM reseller_hierarchies WHERE tenant_id = $1)
+        UNION ALL
+        SELECT p.id, p.parent_reseller_id, p.tenant_id, c.depth + 1
+        FROM reseller_hierarchies p
+        JOIN resellers c ON p.id = c.parent_reseller_id
+        WHERE c.depth < 16
+    ),
+    chain AS (
+        SELECT $1::VARCHAR AS tenant_id, 0 AS depth
+        UNION ALL
+        SELECT tenant_id, depth FROM resellers
+    )
+"#;
+
+/// Public path a published theme's assets are served from
+pub fn public_asset_url(asset_id: Uuid) -> String {
+    format!("/api/v1/branding/assets/{}", asset_id)
+}
+
+/// Tenant themes: a draft edited in place, published as a new version.
+/// Publishing archives the previous version; any version can be restored
+/// into the draft.
+pub struct ThemeService {
+    db_pool: Arc<PgPool>,
+    asset_service: Arc<AssetService>,
+    file_service: Arc<FileServiceClient>,
+}
+
+impl ThemeService {
+    pub fn new(db_pool: Arc<PgPool>, asset_service: Arc<AssetService>, file_service: Arc<FileServiceClient>) -> Self {
+        Self {
+            db_pool,
+            asset_service,
+            file_service,
+        }
+    }
+
+    /// Store an uploaded logo, favicon or other asset in file-service
+    pub async fn upload_asset(
+        &self,
+        tenant_id: &str,
+        asset_type: AssetType,
+        filename: &str,
+        file_data: &[u8],
+    ) -> WhiteLabelResult<BrandingAsset> {
+        let prepared = self.asset_service.prepare_upload(&asset_type, file_data)?;
+        let file_size = prepared.data.len() as i64;
+
+        let file_id = self
+            .file_service
+            .upload(
+                tenant_id,
+                filename,
+                &prepared.mime_type,
+                prepared.data,
+                &serde_json::json!({
+                    "purpose": "branding",
+                    "asset_type": asset_type_name(&asset_type),
+                }),
+            )
+            .await?;
+
+        let asset = sqlx::query_as::<_, BrandingAssetModel>(&format!(
+            r#"
+            INSERT INTO branding_assets (
+                tenant_id, asset_type, original_filename, file_path, file_size, mime_type,
+                dimensions_width, dimensions_height, checksum, file_id
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            RETURNING {}
+            "#,
+            ASSET_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(asset_type_name(&asset_type))
+        .bind(filename)
+        .bind(format!("file-service://{}", file_id))
+        .bind(file_size)
+        .bind(&prepared.mime_type)
+        .bind(prepared.dimensions.as_ref().map(|d| d.width as i32))
+        .bind(prepared.dimensions.as_ref().map(|d| d.height as i32))
+        .bind(&prepared.checksum)
+        .bind(file_id)
+        .fetch_one(&*self.db_pool)
+        .await?;
+
+        tracing::info!("Stored {} branding asset {} for tenant {}", asset_type_name(&asset_type), asset.id, tenant_id);
+        Ok(asset.into())
+    }
+
+    /// Delete an asset no published theme uses
+    pub async fn delete_asset(&self, tenant_id: &str, asset_id: Uuid) -> WhiteLabelResult<()> {
+        let in_use = sqlx::query!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM branding_versions
+                WHERE tenant_id = $1 AND status = 'published'
+                  AND (logo_asset_id = $2 OR favicon_asset_id = $2)
+            ) as "in_use!"
+            "#,
+            tenant_id,
+            asset_id
+        )
+        .fetch_one(&*self.db_pool)
+        .await?
+        .in_use;
+
+        if in_use {
+            return Err(WhiteLabelError::Conflict(
+                "Asset is used by the published theme".to_string(),
+            ));
+        }
+
+        let deleted = sqlx::query!(
+            "DELETE FROM branding_assets WHERE id = $1 AND tenant_id = $2 RETURNING file_id",
+            asset_id,
+            tenant_id
+        )
+        .fetch_optional(&*self.db_pool)
+        .await?
+        .ok_or_else(|| WhiteLabelError::NotFound("Asset not found".to_string()))?;
+
+        if let Some(file_id) = deleted.file_id {
+            // The asset is gone either way; an orphaned file only costs storage
+            if let Err(e) = self.file_service.delete(tenant_id, file_id).await {
+                tracing::warn!("Failed to delete branding asset file {}: {}", file_id, e);
+            }
+        }
+
+        Ok(())
+    }
+
+    pub async fn list_versions(&self, tenant_id: &str) -> WhiteLabelResult<Vec<BrandingVersion>> {
+        let versions = sqlx::query_as::<_, BrandingVersionModel>(&format!(
+            "SELECT {} FROM branding_versions WHERE tenant_id = $1 ORDER BY version DESC",
+            VERSION_COLUMNS
+        ))
+        .bind(tenant_id)
+        .fetch_all(&*self.db_pool)
+        .await?;
+
+        Ok(versions.into_iter().map(BrandingVersion::from).collect())
+    }
+
+    pub async fn get_version(&self, tenant_id: &str, version_id: Uuid) -> WhiteLabelResult<BrandingVersion> {
+        sqlx::query_as::<_, BrandingVersionModel>(&format!(
+            "SELECT {} FROM branding_versions WHERE id = $1 AND tenant_id = $2",
+            VERSION_COLUMNS
+        ))
+        .bind(version_id)
+        .bind(tenant_id)
+        .fetch_optional(&*self.db_pool)
+        .await?
+        .map(BrandingVersion::from)
+        .ok_or_else(|| WhiteLabelError::NotFound("Branding version not found".to_string()))
+    }
+
+    pub async fn get_draft(&self, tenant_id: &str) -> WhiteLabelResult<BrandingVersion> {
+        sqlx::query_as::<_, BrandingVersionModel>(&format!(
+            "SELECT {} FROM branding_versions WHERE tenant_id = $1 AND status = 'draft'",
+            VERSION_COLUMNS
+        ))
+        .bind(tenant_id)
+        .fetch_optional(&*self.db_pool)
+        .await?
+        .map(BrandingVersion::from)
+        .ok_or_else(|| WhiteLabelError::NotFound("No draft theme".to_string()))
+    }
+
+    /// Replace the draft theme, starting a new version if there is no draft
+    pub async fn save_draft(
+        &self,
+        tenant_id: &str,
+        user_id: Option<&str>,
+        theme: BrandingTheme,
+    ) -> WhiteLabelResult<BrandingVersion> {
+        validate_theme(&theme)?;
+        self.check_asset(tenant_id, theme.logo_asset_id, AssetType::Logo).await?;
+        self.check_asset(tenant_id, theme.favicon_asset_id, AssetType::Favicon).await?;
+
+        let updated = sqlx::query_as::<_, BrandingVersionModel>(&format!(
+            r#"
+            UPDATE branding_versions
+            SET brand_name = $2, logo_asset_id = $3, favicon_asset_id = $4, color_scheme = $5,
+                typography = $6, custom_css = $7
+            WHERE tenant_id = $1 AND status = 'draft'
+            RETURNING {}
+            "#,
+            VERSION_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(&theme.brand_name)
+        .bind(theme.logo_asset_id)
+        .bind(theme.favicon_asset_id)
+        .bind(sqlx::types::Json(&theme.color_scheme))
+        .bind(sqlx::types::Json(&theme.typography))
+        .bind(&theme.custom_css)
+        .fetch_optional(&*self.db_pool)
+        .await?;
+
+        if let Some(updated) = updated {
+            return Ok(updated.into());
+        }
+
+        let created = sqlx::query_as::<_, BrandingVersionModel>(&format!(
+            r#"
+            INSERT INTO branding_versions (
+                tenant_id, version, status, brand_name, logo_asset_id, favicon_asset_id,
+                color_scheme, typography, custom_css, created_by
+            )
+            SELECT $1, COALESCE(MAX(version), 0) + 1, 'draft', $2, $3, $4, $5, $6, $7, $8
+            FROM branding_versions WHERE tenant_id = $1
+            RETURNING {}
+            "#,
+            VERSION_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(&theme.brand_name)
+        .bind(theme.logo_asset_id)
+        .bind(theme.favicon_asset_id)
+        .bind(sqlx::types::Json(&theme.color_scheme))
+        .bind(sqlx::types::Json(&theme.typography))
+        .bind(&theme.custom_css)
+        .bind(user_id)
+        .fetch_one(&*self.db_pool)
+        .await?;
+
+        Ok(created.into())
+    }
+
+    pub async fn discard_draft(&self, tenant_id: &str) -> WhiteLabelResult<()> {
+        let result = sqlx::query!(
+            "DELETE FROM branding_versions WHERE tenant_id = $1 AND status = 'draft'",
+            tenant_id
+        )
+        .execute(&*self.db_pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(WhiteLabelError::NotFound("No draft theme".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Publish the draft, archiving the version it replaces
+    pub async fn publish_draft(&self, tenant_id: &str, user_id: Option<&str>) -> WhiteLabelResult<BrandingVersion> {
+        let mut tx = self.db_pool.begin().await?;
+
+        sqlx::query!(
+            "UPDATE branding_versions SET status = 'archived' WHERE tenant_id = $1 AND status = 'published'",
+            tenant_id
+        )
+        .execute(&mut *tx)
+        .await?;
+
+        let published = sqlx::query_as::<_, BrandingVersionModel>(&format!(
+            r#"
+            UPDATE branding_versions
+            SET status = 'published', published_by = $2, published_at = NOW()
+            WHERE tenant_id = $1 AND status = 'draft'
+            RETURNING {}
+            "#,
+            VERSION_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(user_id)
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or_else(|| WhiteLabelError::Conflict("No draft theme to publish".to_string()))?;
+
+        tx.commit().await?;
+
+        tracing::info!("Published branding version {} for tenant {}", published.version, tenant_id);
+        Ok(published.into())
+    }
+
+    /// Copy an earlier version into the draft, to edit or publish again
+    pub async fn restore_version(
+        &self,
+        tenant_id: &str,
+        user_id: Option<&str>,
+        version_id: Uuid,
+    ) -> WhiteLabelResult<BrandingVersion> {
+        let version = self.get_version(tenant_id, version_id).await?;
+        if version.status == BrandingVersionStatus::Draft {
+            return Err(WhiteLabelError::Conflict("Version is already the draft".to_string()));
+        }
+
+        self.save_draft(tenant_id, user_id, version.theme).await
+    }
+
+    /// The tenant's own published theme, ignoring any it inherits
+    pub async fn own_published(&self, tenant_id: &str) -> WhiteLabelResult<Option<BrandingVersion>> {
+        let version = sqlx::query_as::<_, BrandingVersionModel>(&format!(
+            "SELECT {} FROM branding_versions WHERE tenant_id = $1 AND status = 'published'",
+            VERSION_COLUMNS
+        ))
+        .bind(tenant_id)
+        .fetch_optional(&*self.db_pool)
+        .await?;
+
+        Ok(version.map(BrandingVersion::from))
+    }
+
+    /// The theme the tenant is served: its own published theme, or else the
+    /// nearest one published up its reseller chain
+    pub async fn published_for_tenant(&self, tenant_id: &str) -> WhiteLabelResult<Option<BrandingVersion>> {
+        let version = sqlx::query_as::<_, BrandingVersionModel>(&format!(
+            r#"
+            {}
+            SELECT {}
+            FROM branding_versions
+            WHERE status = 'published' AND tenant_id IN (SELECT tenant_id FROM chain)
+            ORDER BY (SELECT MIN(depth) FROM chain WHERE chain.tenant_id = branding_versions.tenant_id)
+            LIMIT 1
+            "#,
+            BRANDING_CHAIN, VERSION_COLUMNS
+        ))
+        .bind(tenant_id)
+        .fetch_optional(&*self.db_pool)
+        .await?;
+
+        Ok(version.map(BrandingVersion::from))
+    }
+
+    /// The theme served to a verified custom domain's tenant
+    pub async fn published_for_domain(&self, domain: &str) -> WhiteLabelResult<Option<PublishedBranding>> {
+        let domain = domain.trim().trim_end_matches('.').to_ascii_lowercase();
+
+        let tenant_id = sqlx::query_scalar!(
+            "SELECT tenant_id FROM custom_domains WHERE domain = $1 AND status = 'verified'",
+            domain
+        )
+        .fetch_optional(&*self.db_pool)
+        .await?;
+        let Some(tenant_id) = tenant_id else {
+            return Ok(None);
+        };
+
+        let version = self.published_for_tenant(&tenant_id).await?;
+        Ok(version.map(|version| PublishedBranding {
+            version: version.version,
+            brand_name: version.theme.brand_name,
+            logo_url: version.theme.logo_asset_id.map(public_asset_url),
+            favicon_url: version.theme.favicon_asset_id.map(public_asset_url),
+            color_scheme: version.theme.color_scheme,
+            typography: version.theme.typography,
+            custom_css: version.theme.custom_css,
+            published_at: version.published_at,
+        }))
+    }
+
+    /// A download URL for an asset of a published theme. Assets of drafts
+    /// stay private until they are published. A customer's theme may use its
+    /// reseller's assets, so the theme need not be the asset owner's.
+    pub async fn published_asset_url(&self, asset_id: Uuid) -> WhiteLabelResult<String> {
+        let asset = sqlx::query!(
+            r#"
+            SELECT a.tenant_id, a.file_id as "file_id!"
+            FROM branding_assets a
+            WHERE a.id = $1 AND a.file_id IS NOT NULL
+              AND EXISTS (
+                  SELECT 1 FROM branding_versions v
+                  WHERE v.status = 'published'
+                    AND (v.logo_asset_id = a.id OR v.favicon_asset_id = a.id)
+              )
+            "#,
+            asset_id
+        )
+        .fetch_optional(&*self.db_pool)
+        .await?
+        .ok_or_else(|| WhiteLabelError::NotFound("Asset not found".to_string()))?;
+
+        let (url, _) = self.file_service.download_url(&asset.tenant_id, asset.file_id).await?;
+        Ok(url)
+    }
+
+    async fn check_asset(&self, tenant_id: &str, asset_id: Option<Uuid>, expected: AssetType) -> WhiteLabelResult<()> {
+        let Some(asset_id) = asset_id else {
+            return Ok(());
+        };
+
+        // Customers may reuse the logos of the resellers they inherit from
+        let asset_type = sqlx::query_scalar::<_, String>(&format!(
+            "{} SELECT asset_type FROM branding_assets WHERE id = $2 AND tenant_id IN (SELECT tenant_id FROM chain)",
+            BRANDING_CHAIN
+        ))
+        .bind(tenant_id)
+        .bind(asset_id)
+        .fetch_optional(&*self.db_pool)
+        .await?
+        .ok_or_else(|| WhiteLabelError::Validation(format!("Unknown asset {}", asset_id)))?;
+
+        if asset_type != asset_type_name(&expected) {
+            return Err(WhiteLabelError::Validation(format!(
+                "Asset {} is a {}, not a {}",
+                asset_id,
+                asset_type,
+                asset_type_name(&expected)
+            )));
+        }
+        Ok(())
+    }
+}
+
+fn asset_type_name(asset_type: &AssetType) -> &'static str {
+    match asset_type {
+        AssetType::Logo => "logo",
+        AssetType::Favicon => "favicon",
+        AssetType::BackgroundImage => "background_image",
+        AssetType::EmailHeader => "email_header",
+        AssetType::EmailFooter => "email_footer",
+        AssetType::CustomIcon => "custom_icon",
+    }
+}
+
+/// Check a theme before it is saved. Colors, fonts and sizes end up in CSS
+/// custom properties, so they must not be able to break out of them.
+pub fn validate_theme(theme: &BrandingTheme) -> WhiteLabelResult<()> {
+    let brand_name = theme.brand_name.trim();
+    if brand_name.is_empty() || brand_name.len() > 255 {
+        return Err(WhiteLabelError::Validation(
+            "Brand name must be 1 to 255 characters".to_string(),
+        ));
+    }
+
+    let colors = &theme.color_scheme;
+    for (name, value) in [
+        ("primary_color", &colors.primary_color),
+        ("secondary_color", &colors.secondary_color),
+        ("accent_color", &colors.accent_color),
+        ("background_color", &colors.background_color),
+        ("text_color", &colors.text_color),
+    ] {
+        if !is_hex_color(value) {
+            return Err(WhiteLabelError::Validation(format!(
+                "{} must be a hex color like #1a2b3c",
+                name
+            )));
+        }
+    }
+
+    let typography = &theme.typography;
+    let sizes = &typography.font_sizes;
+    for (name, value) in [
+        ("font_family", Some(&typography.font_family)),
+        ("heading_font", typography.heading_font.as_ref()),
+        ("font_sizes.small", Some(&sizes.small)),
+        ("font_sizes.medium", Some(&sizes.medium)),
+        ("font_sizes.large", Some(&sizes.large)),
+        ("font_sizes.extra_large", Some(&sizes.extra_large)),
+    ] {
+        if let Some(value) = value {
+            if value.trim().is_empty() || value.len() > 255 || !is_safe_css_value(value) {
+                return Err(WhiteLabelError::Validation(format!("{} is not a valid CSS value", name)));
+            }
+        }
+    }
+
+    if let Some(css) = &theme.custom_css {
+        if css.len() > MAX_CUSTOM_CSS_BYTES {
+            return Err(WhiteLabelError::Validation(format!(
+                "Custom CSS must be at most {} KB",
+                MAX_CUSTOM_CSS_BYTES / 1024
+            )));
+        }
+        let lowered = css.to_ascii_lowercase();
+        // Closing the style element, loading other stylesheets and the old
+        // script-running CSS extensions
+        for forbidden in ["</", "@import", "expression(", "javascript:", "behavior:", "-moz-binding"] {
+            if lowered.contains(forbidden) {
+                return Err(WhiteLabelError::Validation(format!(
+                    "Custom CSS may not contain {}",
+                    forbidden
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+fn is_hex_color(value: &str) -> bool {
+    let Some(digits) = value.strip_prefix('#') else {
+        return false;
+    };
+    matches!(digits.len(), 3 | 6) && digits.chars().all(|c| c.is_ascii_hexdigit())
+}
+
+fn is_safe_css_value(value: &str) -> bool {
+    !value.chars().any(|c| matches!(c, ';' | '{' | '}' | '<' | '>' | '\\') || c.is_control())
+}
+
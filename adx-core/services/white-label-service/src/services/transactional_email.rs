@@ -0,0 +1,694 @@
+use crate::config::WhiteLabelConfig;
+use crate::error::{WhiteLabelError, WhiteLabelResult};
+use crate::models::{EmailTemplateModel, TenantEmailSettingsModel};
+use crate::services::email_templates::{
+    branding_context, default_branding_context, default_template, render_context, sample_context,
+    EmailTemplateEngine,
+};
+use crate::services::email_transport::{validate_smtp_server, EmailTransport, OutgoingEmail};
+use crate::services::theme_service::public_asset_url;
+use crate::services::translation_service::EMAIL_NAMESPACE;
+use crate::services::{ThemeService, TranslationService};
+use crate::types::*;
+use adx_shared::database::encryption::FieldCipher;
+use adx_shared::email::{EmailRecipient, SendEmailRequest, SendEmailResponse, SystemEmail};
+use lettre::message::Mailbox;
+use serde_json::{Map, Value};
+use sqlx::PgPool;
+use std::sync::Arc;
+use uuid::Uuid;
+
+const TEMPLATE_COLUMNS: &str =
+    "id, tenant_id, template_key, locale, format, subject, body, text_body, enabled, updated_by, created_at, updated_at";
+
+const SETTINGS_COLUMNS: &str = "tenant_id, provider, from_email, from_name, reply_to, smtp_host, smtp_port, \
+     smtp_username, smtp_password_encrypted, smtp_starttls, ses_region, ses_access_key_id, \
+     ses_secret_access_key_encrypted, ses_configuration_set, updated_by, updated_at";
+
+/// A delivery claimed as 'sending' for longer than this is assumed to have
+/// died with its sender and may be claimed again
+const STALE_CLAIM_MINUTES: i32 = 10;
+
+enum Claim {
+    New(Uuid),
+    Delivered(SendEmailResponse),
+}
+
+/// Renders system emails with the tenant's templates and branding and
+/// delivers them through the tenant's mail server, or the platform's
+pub struct TransactionalEmailService {
+    db_pool: Arc<PgPool>,
+    cipher: Arc<FieldCipher>,
+    config: Arc<WhiteLabelConfig>,
+    theme_service: Arc<ThemeService>,
+    translation_service: Arc<TranslationService>,
+    engine: EmailTemplateEngine,
+}
+
+impl TransactionalEmailService {
+    pub fn new(
+        db_pool: Arc<PgPool>,
+        cipher: Arc<FieldCipher>,
+        config: Arc<WhiteLabelConfig>,
+        theme_service: Arc<ThemeService>,
+        translation_service: Arc<TranslationService>,
+    ) -> Self {
+        Self {
+            db_pool,
+            cipher,
+            config,
+            theme_service,
+            translation_service,
+            engine: EmailTemplateEngine::new(),
+        }
+    }
+
+    /// Render and deliver a system email. A request repeating an earlier
+    /// one's idempotency key returns the earlier delivery.
+    pub async fn send(&self, request: SendEmailRequest) -> WhiteLabelResult<SendEmailResponse> {
+        request
+            .validate()
+            .map_err(|e| WhiteLabelError::Validation(e.to_string()))?;
+        let tenant_id = request.tenant_id.as_deref();
+
+        let branding = self.branding(tenant_id).await?;
+        let mut context = render_context(&request.variables, &branding);
+        context.insert(
+            "strings".to_string(),
+            self.strings(tenant_id, request.locale.as_deref()).await?,
+        );
+        let content = self
+            .render(tenant_id, request.template, request.locale.as_deref(), &context)
+            .await?;
+
+        let settings = match tenant_id {
+            Some(tenant_id) => self.load_settings(tenant_id).await?,
+            None => None,
+        };
+        let (transport, from, reply_to) = self.transport(settings.as_ref(), &branding).await?;
+        let to = request.to.iter().map(mailbox).collect::<WhiteLabelResult<Vec<_>>>()?;
+
+        let delivery_id = match self.claim(&request, transport.provider()).await? {
+            Claim::New(delivery_id) => delivery_id,
+            Claim::Delivered(response) => return Ok(response),
+        };
+
+        let email = OutgoingEmail {
+            message_id: format!("{}@{}", delivery_id, from.email.domain()),
+            from,
+            reply_to,
+            to,
+            content,
+        };
+        match transport.send(&email).await {
+            Ok(message_id) => {
+                sqlx::query!(
+                    "UPDATE email_deliveries SET status = 'sent', message_id = $2, sent_at = NOW() WHERE id = $1",
+                    delivery_id,
+                    message_id
+                )
+                .execute(&*self.db_pool)
+                .await?;
+
+                tracing::info!(
+                    "Sent {} email {} for tenant {:?} via {}",
+                    request.template.as_str(),
+                    delivery_id,
+                    tenant_id,
+                    transport.provider().as_str()
+                );
+                Ok(SendEmailResponse {
+                    delivery_id: delivery_id.to_string(),
+                    provider: transport.provider().as_str().to_string(),
+                    message_id: Some(message_id),
+                    duplicate: false,
+                })
+            }
+            Err(e) => {
+                // Release the claim so a retry can send
+                sqlx::query!("DELETE FROM email_deliveries WHERE id = $1", delivery_id)
+                    .execute(&*self.db_pool)
+                    .await?;
+                Err(e)
+            }
+        }
+    }
+
+    /// Each system email with the tenant's overrides of it
+    pub async fn list_templates(&self, tenant_id: &str) -> WhiteLabelResult<Vec<EmailTemplateSummary>> {
+        let overrides: Vec<EmailTemplateOverride> = sqlx::query_as::<_, EmailTemplateModel>(&format!(
+            "SELECT {} FROM email_templates WHERE tenant_id = $1 ORDER BY template_key, locale",
+            TEMPLATE_COLUMNS
+        ))
+        .bind(tenant_id)
+        .fetch_all(&*self.db_pool)
+        .await?
+        .into_iter()
+        .filter_map(EmailTemplateModel::into_override)
+        .collect();
+
+        Ok(SystemEmail::ALL
+            .into_iter()
+            .map(|email| EmailTemplateSummary {
+                template: email,
+                variables: email.variables().iter().map(|name| name.to_string()).collect(),
+                overrides: overrides
+                    .iter()
+                    .filter(|template| template.template == email)
+                    .cloned()
+                    .collect(),
+            })
+            .collect())
+    }
+
+    /// Create or replace the tenant's override for a locale
+    pub async fn save_template(
+        &self,
+        tenant_id: &str,
+        user_id: Option<&str>,
+        email: SystemEmail,
+        request: SaveEmailTemplateRequest,
+    ) -> WhiteLabelResult<EmailTemplateOverride> {
+        let locale = normalize_locale(request.locale.as_deref())?;
+        self.engine.validate(email, &request.content)?;
+
+        let saved = sqlx::query_as::<_, EmailTemplateModel>(&format!(
+            r#"
+            INSERT INTO email_templates (tenant_id, template_key, locale, format, subject, body, text_body, enabled, updated_by)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)
+            ON CONFLICT (tenant_id, template_key, locale) DO UPDATE
+            SET format = EXCLUDED.format, subject = EXCLUDED.subject, body = EXCLUDED.body,
+                text_body = EXCLUDED.text_body, enabled = EXCLUDED.enabled, updated_by = EXCLUDED.updated_by
+            RETURNING {}
+            "#,
+            TEMPLATE_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(email.as_str())
+        .bind(&locale)
+        .bind(format_name(request.content.format))
+        .bind(request.content.subject.trim())
+        .bind(&request.content.body)
+        .bind(&request.content.text_body)
+        .bind(request.enabled.unwrap_or(true))
+        .bind(user_id)
+        .fetch_one(&*self.db_pool)
+        .await?;
+
+        saved
+            .into_override()
+            .ok_or_else(|| WhiteLabelError::Internal("Saved template has an unknown key".to_string()))
+    }
+
+    /// Drop the tenant's override for a locale, reverting to the default
+    pub async fn delete_template(&self, tenant_id: &str, email: SystemEmail, locale: Option<&str>) -> WhiteLabelResult<()> {
+        let locale = normalize_locale(locale)?;
+        let result = sqlx::query!(
+            "DELETE FROM email_templates WHERE tenant_id = $1 AND template_key = $2 AND locale = $3",
+            tenant_id,
+            email.as_str(),
+            locale
+        )
+        .execute(&*self.db_pool)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(WhiteLabelError::NotFound("Email template override not found".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Render an email as the tenant's users would get it, from unsaved
+    /// content or the template in effect
+    pub async fn preview(
+        &self,
+        tenant_id: &str,
+        email: SystemEmail,
+        request: PreviewEmailTemplateRequest,
+    ) -> WhiteLabelResult<RenderedEmail> {
+        let branding = self.branding(Some(tenant_id)).await?;
+        let mut context = sample_context(email, &request.variables, &branding);
+        context.insert(
+            "strings".to_string(),
+            self.strings(Some(tenant_id), request.locale.as_deref()).await?,
+        );
+
+        match request.content {
+            Some(content) => {
+                self.engine.validate(email, &content)?;
+                let fallback = default_template(email).text_body;
+                self.engine.render(&content, fallback.as_deref(), &context)
+            }
+            None => {
+                self.render(Some(tenant_id), email, request.locale.as_deref(), &context)
+                    .await
+            }
+        }
+    }
+
+    pub async fn get_settings(&self, tenant_id: &str) -> WhiteLabelResult<TenantEmailSettings> {
+        self.load_settings(tenant_id)
+            .await?
+            .map(TenantEmailSettings::from)
+            .ok_or_else(|| WhiteLabelError::NotFound("No email settings; the platform's mail server is used".to_string()))
+    }
+
+    pub async fn save_settings(
+        &self,
+        tenant_id: &str,
+        user_id: Option<&str>,
+        request: UpdateEmailSettingsRequest,
+    ) -> WhiteLabelResult<TenantEmailSettings> {
+        let existing = self.load_settings(tenant_id).await?;
+        mailbox(&EmailRecipient::new(request.from_email.clone()))?;
+        if let Some(reply_to) = &request.reply_to {
+            mailbox(&EmailRecipient::new(reply_to.clone()))?;
+        }
+
+        let (smtp_password, ses_secret) = match request.provider {
+            EmailProvider::Smtp => {
+                let host = request
+                    .smtp_host
+                    .as_deref()
+                    .ok_or_else(|| WhiteLabelError::Validation("smtp_host is required".to_string()))?;
+                let port = request
+                    .smtp_port
+                    .ok_or_else(|| WhiteLabelError::Validation("smtp_port is required".to_string()))?;
+                validate_smtp_server(host, port)?;
+
+                let password = match &request.smtp_password {
+                    Some(password) => Some(self.encrypt(tenant_id, "smtp_password", password).await?),
+                    None => existing.as_ref().and_then(|settings| settings.smtp_password_encrypted.clone()),
+                };
+                (password, None)
+            }
+            EmailProvider::Ses => {
+                if request.ses_region.is_none() || request.ses_access_key_id.is_none() {
+                    return Err(WhiteLabelError::Validation(
+                        "ses_region and ses_access_key_id are required".to_string(),
+                    ));
+                }
+                let secret = match &request.ses_secret_access_key {
+                    Some(secret) => Some(self.encrypt(tenant_id, "ses_secret_access_key", secret).await?),
+                    None => existing
+                        .as_ref()
+                        .and_then(|settings| settings.ses_secret_access_key_encrypted.clone()),
+                };
+                if secret.is_none() {
+                    return Err(WhiteLabelError::Validation("ses_secret_access_key is required".to_string()));
+                }
+                (None, secret)
+            }
+        };
+
+        let saved = sqlx::query_as::<_, TenantEmailSettingsModel>(&format!(
+            r#"
+            INSERT INTO tenant_email_settings (
+                tenant_id, provider, from_email, from_name, reply_to, smtp_host, smtp_port, smtp_username,
+                smtp_password_encrypted, smtp_starttls, ses_region, ses_access_key_id,
+                ses_secret_access_key_encrypted, ses_configuration_set, updated_by
+            )
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)
+            ON CONFLICT (tenant_id) DO UPDATE
+            SET provider = EXCLUDED.provider, from_email = EXCLUDED.from_email, from_name = EXCLUDED.from_name,
+                reply_to = EXCLUDED.reply_to, smtp_host = EXCLUDED.smtp_host, smtp_port = EXCLUDED.smtp_port,
+                smtp_username = EXCLUDED.smtp_username, smtp_password_encrypted = EXCLUDED.smtp_password_encrypted,
+                smtp_starttls = EXCLUDED.smtp_starttls, ses_region = EXCLUDED.ses_region,
+                ses_access_key_id = EXCLUDED.ses_access_key_id,
+                ses_secret_access_key_encrypted = EXCLUDED.ses_secret_access_key_encrypted,
+                ses_configuration_set = EXCLUDED.ses_configuration_set, updated_by = EXCLUDED.updated_by
+            RETURNING {}
+            "#,
+            SETTINGS_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(request.provider.as_str())
+        .bind(request.from_email.trim())
+        .bind(&request.from_name)
+        .bind(&request.reply_to)
+        .bind(&request.smtp_host)
+        .bind(request.smtp_port.map(i32::from))
+        .bind(&request.smtp_username)
+        .bind(smtp_password)
+        .bind(request.smtp_starttls.unwrap_or(true))
+        .bind(&request.ses_region)
+        .bind(&request.ses_access_key_id)
+        .bind(ses_secret)
+        .bind(&request.ses_configuration_set)
+        .bind(user_id)
+        .fetch_one(&*self.db_pool)
+        .await?;
+
+        tracing::info!("Saved {} email settings for tenant {}", request.provider.as_str(), tenant_id);
+        Ok(saved.into())
+    }
+
+    /// Go back to sending through the platform's mail server
+    pub async fn delete_settings(&self, tenant_id: &str) -> WhiteLabelResult<()> {
+        let result = sqlx::query!("DELETE FROM tenant_email_settings WHERE tenant_id = $1", tenant_id)
+            .execute(&*self.db_pool)
+            .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(WhiteLabelError::NotFound("No email settings".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Send a test message through the tenant's mail delivery
+    pub async fn send_test(&self, tenant_id: &str, to: &str) -> WhiteLabelResult<String> {
+        let branding = self.branding(Some(tenant_id)).await?;
+        let settings = self.load_settings(tenant_id).await?;
+        let (transport, from, reply_to) = self.transport(settings.as_ref(), &branding).await?;
+
+        let brand_name = branding.get("brand_name").and_then(Value::as_str).unwrap_or_default();
+        let email = OutgoingEmail {
+            message_id: format!("{}@{}", Uuid::new_v4(), from.email.domain()),
+            from,
+            reply_to,
+            to: vec![mailbox(&EmailRecipient::new(to))?],
+            content: RenderedEmail {
+                subject: format!("{} test email", brand_name),
+                html_body: format!(
+                    "<p>This test email was sent through {} delivery for {}.</p>",
+                    transport.provider().as_str().to_uppercase(),
+                    html_escape(brand_name)
+                ),
+                text_body: format!(
+                    "This test email was sent through {} delivery for {}.",
+                    transport.provider().as_str().to_uppercase(),
+                    brand_name
+                ),
+            },
+        };
+
+        transport.send(&email).await
+    }
+
+    /// The tenant's enabled override for the locale, falling back from
+    /// "pt-br" to "pt" to the override without a locale, or else the
+    /// platform default. An override that fails to render falls back to
+    /// the default so the email still goes out.
+    async fn render(
+        &self,
+        tenant_id: Option<&str>,
+        email: SystemEmail,
+        locale: Option<&str>,
+        context: &Map<String, Value>,
+    ) -> WhiteLabelResult<RenderedEmail> {
+        let default = default_template(email);
+
+        if let Some(tenant_id) = tenant_id {
+            let overrides = sqlx::query_as::<_, EmailTemplateModel>(&format!(
+                "SELECT {} FROM email_templates WHERE tenant_id = $1 AND template_key = $2 AND enabled",
+                TEMPLATE_COLUMNS
+            ))
+            .bind(tenant_id)
+            .bind(email.as_str())
+            .fetch_all(&*self.db_pool)
+            .await?;
+
+            let locale = locale.map(str::to_ascii_lowercase).unwrap_or_default();
+            let language = locale.split('-').next().unwrap_or_default().to_string();
+            let chosen = [locale, language, String::new()]
+                .iter()
+                .find_map(|candidate| overrides.iter().find(|template| &template.locale == candidate))
+                .cloned()
+                .and_then(EmailTemplateModel::into_override);
+
+            if let Some(chosen) = chosen {
+                match self
+                    .engine
+                    .render(&chosen.content, default.text_body.as_deref(), context)
+                {
+                    Ok(rendered) => return Ok(rendered),
+                    Err(e) => tracing::warn!(
+                        "Email template {} of tenant {} failed to render, using the default: {}",
+                        chosen.id,
+                        tenant_id,
+                        e
+                    ),
+                }
+            }
+        }
+
+        self.engine.render(&default, None, context)
+    }
+
+    /// The tenant's email copy in the locale, from its translations of the
+    /// `email` namespace
+    async fn strings(&self, tenant_id: Option<&str>, locale: Option<&str>) -> WhiteLabelResult<Value> {
+        let Some(tenant_id) = tenant_id else {
+            return Ok(Value::Object(Map::new()));
+        };
+        let locale = match locale.filter(|locale| !locale.trim().is_empty()) {
+            Some(locale) => locale.to_string(),
+            None => self.translation_service.get_settings(tenant_id).await?.default_locale,
+        };
+
+        // A locale the platform cannot parse gets no copy rather than no email
+        let strings = match self
+            .translation_service
+            .resolve(tenant_id, &locale, EMAIL_NAMESPACE)
+            .await
+        {
+            Ok(resolved) => resolved.strings,
+            Err(WhiteLabelError::Validation(_)) => Default::default(),
+            Err(e) => return Err(e),
+        };
+        Ok(Value::Object(
+            strings.into_iter().map(|(key, value)| (key, Value::String(value))).collect(),
+        ))
+    }
+
+    /// The tenant's published theme as template variables. Logos are linked
+    /// on the tenant's verified domain so mail clients load them from it.
+    async fn branding(&self, tenant_id: Option<&str>) -> WhiteLabelResult<Map<String, Value>> {
+        let Some(tenant_id) = tenant_id else {
+            return Ok(default_branding_context());
+        };
+        let Some(version) = self.theme_service.published_for_tenant(tenant_id).await? else {
+            return Ok(default_branding_context());
+        };
+
+        let logo_url = match version.theme.logo_asset_id {
+            Some(asset_id) => {
+                let domain = sqlx::query_scalar!(
+                    "SELECT domain FROM custom_domains WHERE tenant_id = $1 AND status = 'verified' ORDER BY created_at LIMIT 1",
+                    tenant_id
+                )
+                .fetch_optional(&*self.db_pool)
+                .await?;
+                let base_url = domain
+                    .map(|domain| format!("https://{}", domain))
+                    .unwrap_or_else(|| self.config.email_config.public_base_url.trim_end_matches('/').to_string());
+                Some(format!("{}{}", base_url, public_asset_url(asset_id)))
+            }
+            None => None,
+        };
+
+        Ok(branding_context(
+            &version.theme.brand_name,
+            &version.theme.color_scheme,
+            &version.theme.typography.font_family,
+            logo_url,
+        ))
+    }
+
+    /// The tenant's own transport and sender, or the platform's with the
+    /// tenant's brand name
+    async fn transport(
+        &self,
+        settings: Option<&TenantEmailSettingsModel>,
+        branding: &Map<String, Value>,
+    ) -> WhiteLabelResult<(EmailTransport, Mailbox, Option<Mailbox>)> {
+        let brand_name = branding.get("brand_name").and_then(Value::as_str).map(str::to_string);
+
+        let Some(settings) = settings else {
+            let platform = &self.config.email_config;
+            let credentials = Some(platform.smtp_username.clone())
+                .filter(|username| !username.is_empty())
+                .map(|username| (username, platform.smtp_password.clone()));
+            let transport = EmailTransport::smtp(&platform.smtp_host, platform.smtp_port, credentials, true)?;
+            let from = mailbox(&EmailRecipient {
+                email: platform.from_email.clone(),
+                name: brand_name.or_else(|| Some(platform.from_name.clone())),
+            })?;
+            return Ok((transport, from, None));
+        };
+
+        let tenant_id = &settings.tenant_id;
+        let transport = match settings.provider() {
+            EmailProvider::Smtp => {
+                let password = match &settings.smtp_password_encrypted {
+                    Some(encrypted) => Some(self.decrypt(tenant_id, "smtp_password", encrypted).await?),
+                    None => None,
+                };
+                let credentials = settings.smtp_username.clone().zip(password);
+                EmailTransport::smtp(
+                    settings.smtp_host.as_deref().unwrap_or_default(),
+                    settings.smtp_port.unwrap_or(587) as u16,
+                    credentials,
+                    settings.smtp_starttls,
+                )?
+            }
+            EmailProvider::Ses => {
+                let encrypted = settings
+                    .ses_secret_access_key_encrypted
+                    .as_deref()
+                    .ok_or_else(|| WhiteLabelError::Configuration("SES secret access key missing".to_string()))?;
+                let secret = self.decrypt(tenant_id, "ses_secret_access_key", encrypted).await?;
+                EmailTransport::ses(
+                    settings.ses_region.as_deref().unwrap_or_default(),
+                    settings.ses_access_key_id.as_deref().unwrap_or_default(),
+                    &secret,
+                    settings.ses_configuration_set.clone(),
+                )
+            }
+        };
+
+        let from = mailbox(&EmailRecipient {
+            email: settings.from_email.clone(),
+            name: settings.from_name.clone().or(brand_name),
+        })?;
+        let reply_to = settings
+            .reply_to
+            .as_ref()
+            .map(|reply_to| mailbox(&EmailRecipient::new(reply_to.clone())))
+            .transpose()?;
+        Ok((transport, from, reply_to))
+    }
+
+    async fn load_settings(&self, tenant_id: &str) -> WhiteLabelResult<Option<TenantEmailSettingsModel>> {
+        Ok(sqlx::query_as::<_, TenantEmailSettingsModel>(&format!(
+            "SELECT {} FROM tenant_email_settings WHERE tenant_id = $1",
+            SETTINGS_COLUMNS
+        ))
+        .bind(tenant_id)
+        .fetch_optional(&*self.db_pool)
+        .await?)
+    }
+
+    /// Record the delivery before sending it. A request whose key was
+    /// already sent gets that delivery back; one still being sent elsewhere
+    /// fails so its caller retries once the other attempt has finished.
+    async fn claim(&self, request: &SendEmailRequest, provider: EmailProvider) -> WhiteLabelResult<Claim> {
+        let recipients: Vec<String> = request.to.iter().map(|to| to.email.clone()).collect();
+
+        let claimed = sqlx::query_scalar!(
+            r#"
+            INSERT INTO email_deliveries (tenant_id, template_key, recipients, idempotency_key, provider, tags)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            ON CONFLICT ((COALESCE(tenant_id, '')), idempotency_key) WHERE idempotency_key IS NOT NULL DO NOTHING
+            RETURNING id
+            "#,
+            request.tenant_id,
+            request.template.as_str(),
+            &recipients,
+            request.idempotency_key,
+            provider.as_str(),
+            &request.tags
+        )
+        .fetch_optional(&*self.db_pool)
+        .await?;
+        if let Some(id) = claimed {
+            return Ok(Claim::New(id));
+        }
+
+        let existing = sqlx::query!(
+            r#"
+            SELECT id, status, provider, message_id,
+                   created_at < NOW() - make_interval(mins => $3) as "stale!"
+            FROM email_deliveries
+            WHERE COALESCE(tenant_id, '') = COALESCE($1, '') AND idempotency_key = $2
+            "#,
+            request.tenant_id,
+            request.idempotency_key,
+            STALE_CLAIM_MINUTES
+        )
+        .fetch_one(&*self.db_pool)
+        .await?;
+
+        if existing.status == "sent" {
+            return Ok(Claim::Delivered(SendEmailResponse {
+                delivery_id: existing.id.to_string(),
+                provider: existing.provider,
+                message_id: existing.message_id,
+                duplicate: true,
+            }));
+        }
+
+        if existing.stale {
+            let reclaimed = sqlx::query_scalar!(
+                r#"
+                UPDATE email_deliveries SET created_at = NOW(), provider = $2
+                WHERE id = $1 AND status = 'sending' AND created_at < NOW() - make_interval(mins => $3)
+                RETURNING id
+                "#,
+                existing.id,
+                provider.as_str(),
+                STALE_CLAIM_MINUTES
+            )
+            .fetch_optional(&*self.db_pool)
+            .await?;
+            if let Some(id) = reclaimed {
+                return Ok(Claim::New(id));
+            }
+        }
+
+        Err(WhiteLabelError::ExternalService(
+            "Email with this idempotency key is already being sent".to_string(),
+        ))
+    }
+
+    async fn encrypt(&self, tenant_id: &str, field: &str, value: &str) -> WhiteLabelResult<String> {
+        self.cipher
+            .encrypt(tenant_id, field, value)
+            .await
+            .map_err(|e| WhiteLabelError::Internal(format!("Encrypting {} failed: {}", field, e)))
+    }
+
+    async fn decrypt(&self, tenant_id: &str, field: &str, value: &str) -> WhiteLabelResult<String> {
+        self.cipher
+            .decrypt(tenant_id, field, value)
+            .await
+            .map_err(|e| WhiteLabelError::Internal(format!("Decrypting {} failed: {}", field, e)))
+    }
+}
+
+fn mailbox(recipient: &EmailRecipient) -> WhiteLabelResult<Mailbox> {
+    let address = recipient
+        .email
+        .trim()
+        .parse()
+        .map_err(|e| WhiteLabelError::Validation(format!("Invalid email address {}: {}", recipient.email, e)))?;
+    Ok(Mailbox::new(recipient.name.clone(), address))
+}
+
+/// Lowercased BCP 47 tag, or "" for the override used for every locale
+pub fn normalize_locale(locale: Option<&str>) -> WhiteLabelResult<String> {
+    let locale = locale.unwrap_or_default().trim().to_ascii_lowercase();
+    let valid = locale.len() <= 35 && locale.split('-').all(|part| {
+        !part.is_empty() && part.len() <= 8 && part.chars().all(|c| c.is_ascii_alphanumeric())
+    });
+    if locale.is_empty() || valid {
+        Ok(locale)
+    } else {
+        Err(WhiteLabelError::Validation(format!("Invalid locale {}", locale)))
+    }
+}
+
+fn format_name(format: EmailTemplateFormat) -> &'static str {
+    match format {
+        EmailTemplateFormat::Mjml => "mjml",
+        EmailTemplateFormat::Handlebars => "handlebars",
+    }
+}
+
+fn html_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
@@ -0,0 +1,542 @@
+use crate::error::{WhiteLabelError, WhiteLabelResult};
+use crate::models::{TranslationOverrideModel, TranslationSettingsModel};
+use crate::services::transactional_email::normalize_locale;
+use crate::types::*;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
+use sqlx::PgPool;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+use std::sync::Arc;
+
+const OVERRIDE_COLUMNS: &str = "tenant_id, locale, namespace, message_key, value, updated_by, updated_at";
+
+const SETTINGS_COLUMNS: &str = "tenant_id, default_locale, fallbacks, revision, updated_at";
+
+/// Namespace system email templates read, as `{{strings.<key>}}`
+pub const EMAIL_NAMESPACE: &str = "email";
+
+/// Locale every chain ends in for tenants that have not chosen a default
+const PLATFORM_DEFAULT_LOCALE: &str = "en";
+
+const MAX_KEY_LENGTH: usize = 255;
+const MAX_VALUE_BYTES: usize = 10 * 1024;
+
+/// Most strings a single save or import may change
+const MAX_STRINGS_PER_CHANGE: usize = 10_000;
+
+/// Tenant overrides of UI strings and email copy. The frontend layers a
+/// namespace's resolved strings over its bundled translations.
+pub struct TranslationService {
+    db_pool: Arc<PgPool>,
+}
+
+impl TranslationService {
+    pub fn new(db_pool: Arc<PgPool>) -> Self {
+        Self { db_pool }
+    }
+
+    pub async fn get_settings(&self, tenant_id: &str) -> WhiteLabelResult<TranslationSettings> {
+        let settings = sqlx::query_as::<_, TranslationSettingsModel>(&format!(
+            "SELECT {} FROM translation_settings WHERE tenant_id = $1",
+            SETTINGS_COLUMNS
+        ))
+        .bind(tenant_id)
+        .fetch_optional(&*self.db_pool)
+        .await?;
+
+        Ok(settings.map(TranslationSettings::from).unwrap_or(TranslationSettings {
+            tenant_id: tenant_id.to_string(),
+            default_locale: PLATFORM_DEFAULT_LOCALE.to_string(),
+            fallbacks: HashMap::new(),
+            revision: 0,
+            updated_at: None,
+        }))
+    }
+
+    pub async fn save_settings(
+        &self,
+        tenant_id: &str,
+        request: UpdateTranslationSettingsRequest,
+    ) -> WhiteLabelResult<TranslationSettings> {
+        let default_locale = required_locale(&request.default_locale)?;
+        let mut fallbacks = HashMap::new();
+        for (locale, chain) in request.fallbacks {
+            let chain = chain
+                .iter()
+                .map(|fallback| required_locale(fallback))
+                .collect::<WhiteLabelResult<Vec<_>>>()?;
+            fallbacks.insert(required_locale(&locale)?, chain);
+        }
+
+        let saved = sqlx::query_as::<_, TranslationSettingsModel>(&format!(
+            r#"
+            INSERT INTO translation_settings (tenant_id, default_locale, fallbacks, revision)
+            VALUES ($1, $2, $3, 1)
+            ON CONFLICT (tenant_id) DO UPDATE
+            SET default_locale = EXCLUDED.default_locale, fallbacks = EXCLUDED.fallbacks,
+                revision = translation_settings.revision + 1, updated_at = NOW()
+            RETURNING {}
+            "#,
+            SETTINGS_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(&default_locale)
+        .bind(sqlx::types::Json(&fallbacks))
+        .fetch_one(&*self.db_pool)
+        .await?;
+
+        Ok(saved.into())
+    }
+
+    pub async fn list(
+        &self,
+        tenant_id: &str,
+        locale: Option<&str>,
+        namespace: Option<&str>,
+    ) -> WhiteLabelResult<Vec<TranslationOverride>> {
+        let locale = locale.map(required_locale).transpose()?;
+        let overrides = sqlx::query_as::<_, TranslationOverrideModel>(&format!(
+            r#"
+            SELECT {} FROM translation_overrides
+            WHERE tenant_id = $1
+              AND ($2::VARCHAR IS NULL OR locale = $2)
+              AND ($3::VARCHAR IS NULL OR namespace = $3)
+            ORDER BY locale, namespace, message_key
+            "#,
+            OVERRIDE_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(locale)
+        .bind(namespace)
+        .fetch_all(&*self.db_pool)
+        .await?;
+
+        Ok(overrides.into_iter().map(TranslationOverride::from).collect())
+    }
+
+    /// Set, or for `None` values remove, strings of a locale's namespace.
+    /// Returns how many strings changed.
+    pub async fn save_strings(
+        &self,
+        tenant_id: &str,
+        locale: &str,
+        namespace: &str,
+        strings: BTreeMap<String, Option<String>>,
+        user_id: Option<&str>,
+    ) -> WhiteLabelResult<usize> {
+        let mut changes = BTreeMap::new();
+        changes.insert(namespace.to_string(), strings);
+        self.write(tenant_id, locale, changes, user_id).await
+    }
+
+    /// Remove every string the tenant has for a locale
+    pub async fn delete_locale(&self, tenant_id: &str, locale: &str) -> WhiteLabelResult<()> {
+        let locale = required_locale(locale)?;
+
+        let mut tx = self.db_pool.begin().await?;
+        let result = sqlx::query!(
+            "DELETE FROM translation_overrides WHERE tenant_id = $1 AND locale = $2",
+            tenant_id,
+            locale
+        )
+        .execute(&mut *tx)
+        .await?;
+        if result.rows_affected() == 0 {
+            return Err(WhiteLabelError::NotFound(format!("No translations for {}", locale)));
+        }
+        bump_revision(&mut tx, tenant_id).await?;
+        tx.commit().await?;
+
+        Ok(())
+    }
+
+    /// A namespace's strings in a locale: the tenant's strings from each
+    /// locale of the fallback chain, the most specific winning
+    pub async fn resolve(&self, tenant_id: &str, locale: &str, namespace: &str) -> WhiteLabelResult<ResolvedTranslations> {
+        let locale = required_locale(locale)?;
+        validate_namespace(namespace)?;
+        let settings = self.get_settings(tenant_id).await?;
+        let chain = fallback_chain(&locale, &settings);
+
+        let overrides = sqlx::query_as::<_, TranslationOverrideModel>(&format!(
+            "SELECT {} FROM translation_overrides WHERE tenant_id = $1 AND namespace = $2 AND locale = ANY($3)",
+            OVERRIDE_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(namespace)
+        .bind(&chain)
+        .fetch_all(&*self.db_pool)
+        .await?;
+
+        let mut strings = BTreeMap::new();
+        for chain_locale in chain.iter().rev() {
+            for entry in overrides.iter().filter(|entry| &entry.locale == chain_locale) {
+                strings.insert(entry.message_key.clone(), entry.value.clone());
+            }
+        }
+
+        Ok(ResolvedTranslations {
+            locale,
+            namespace: namespace.to_string(),
+            fallback_chain: chain,
+            revision: settings.revision,
+            strings,
+        })
+    }
+
+    /// The tenant of a verified custom domain, and its translation revision
+    pub async fn revision_for_domain(&self, domain: &str) -> WhiteLabelResult<Option<(String, i64)>> {
+        let domain = domain.trim().trim_end_matches('.').to_ascii_lowercase();
+        let tenant_id = sqlx::query_scalar!(
+            "SELECT tenant_id FROM custom_domains WHERE domain = $1 AND status = 'verified'",
+            domain
+        )
+        .fetch_optional(&*self.db_pool)
+        .await?;
+        let Some(tenant_id) = tenant_id else {
+            return Ok(None);
+        };
+
+        let revision = self.get_settings(&tenant_id).await?.revision;
+        Ok(Some((tenant_id, revision)))
+    }
+
+    /// A locale's strings for a translation agency. XLIFF units carry the
+    /// tenant's string in the source locale as their source.
+    pub async fn export(&self, tenant_id: &str, query: &TranslationExportQuery) -> WhiteLabelResult<String> {
+        let locale = required_locale(&query.locale)?;
+        let targets = self.bundle(tenant_id, &locale, query.namespace.as_deref()).await?;
+
+        match query.format.unwrap_or(TranslationFormat::Xliff) {
+            TranslationFormat::Json => serde_json::to_string_pretty(&targets)
+                .map_err(|e| WhiteLabelError::Internal(format!("Failed to encode translations: {}", e))),
+            TranslationFormat::Xliff => {
+                let source_locale = match &query.source_locale {
+                    Some(source_locale) => required_locale(source_locale)?,
+                    None => self.get_settings(tenant_id).await?.default_locale,
+                };
+                let sources = self
+                    .bundle(tenant_id, &source_locale, query.namespace.as_deref())
+                    .await?;
+                Ok(to_xliff(&sources, &targets))
+            }
+        }
+    }
+
+    /// Save the translations of an agency's document, replacing the
+    /// tenant's strings for the keys it translates
+    pub async fn import(
+        &self,
+        tenant_id: &str,
+        query: &TranslationImportQuery,
+        document: &str,
+        user_id: Option<&str>,
+    ) -> WhiteLabelResult<TranslationImportResult> {
+        let bundle = match query.format.unwrap_or(TranslationFormat::Xliff) {
+            TranslationFormat::Json => serde_json::from_str::<TranslationBundle>(document)
+                .map_err(|e| WhiteLabelError::Validation(format!("Invalid translation bundle: {}", e)))?,
+            TranslationFormat::Xliff => parse_xliff(document)?,
+        };
+        let locale = match &query.locale {
+            Some(locale) => required_locale(locale)?,
+            None => required_locale(&bundle.locale)?,
+        };
+
+        let imported = bundle.namespaces.values().map(BTreeMap::len).sum::<usize>();
+        let namespaces: Vec<String> = bundle.namespaces.keys().cloned().collect();
+        let changes = bundle
+            .namespaces
+            .into_iter()
+            .map(|(namespace, strings)| {
+                let strings = strings.into_iter().map(|(key, value)| (key, Some(value))).collect();
+                (namespace, strings)
+            })
+            .collect();
+        self.write(tenant_id, &locale, changes, user_id).await?;
+
+        tracing::info!("Imported {} {} strings for tenant {}", imported, locale, tenant_id);
+        Ok(TranslationImportResult {
+            locale,
+            namespaces,
+            imported,
+        })
+    }
+
+    /// Apply changes to a locale's namespaces in one transaction
+    async fn write(
+        &self,
+        tenant_id: &str,
+        locale: &str,
+        changes: BTreeMap<String, BTreeMap<String, Option<String>>>,
+        user_id: Option<&str>,
+    ) -> WhiteLabelResult<usize> {
+        let locale = required_locale(locale)?;
+        let count = changes.values().map(BTreeMap::len).sum::<usize>();
+        if count > MAX_STRINGS_PER_CHANGE {
+            return Err(WhiteLabelError::Validation(format!(
+                "At most {} strings can be changed at once",
+                MAX_STRINGS_PER_CHANGE
+            )));
+        }
+        for (namespace, strings) in &changes {
+            validate_namespace(namespace)?;
+            for (key, value) in strings {
+                validate_string(key, value.as_deref())?;
+            }
+        }
+
+        let mut tx = self.db_pool.begin().await?;
+        for (namespace, strings) in &changes {
+            for (key, value) in strings {
+                match value {
+                    Some(value) => {
+                        sqlx::query!(
+                            r#"
+                            INSERT INTO translation_overrides (tenant_id, locale, namespace, message_key, value, updated_by)
+                            VALUES ($1, $2, $3, $4, $5, $6)
+                            ON CONFLICT (tenant_id, locale, namespace, message_key) DO UPDATE
+                            SET value = EXCLUDED.value, updated_by = EXCLUDED.updated_by, updated_at = NOW()
+                            "#,
+                            tenant_id,
+                            locale,
+                            namespace,
+                            key,
+                            value,
+                            user_id
+                        )
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+                    None => {
+                        sqlx::query!(
+                            "DELETE FROM translation_overrides WHERE tenant_id = $1 AND locale = $2 AND namespace = $3 AND message_key = $4",
+                            tenant_id,
+                            locale,
+                            namespace,
+                            key
+                        )
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+                }
+            }
+        }
+        bump_revision(&mut tx, tenant_id).await?;
+        tx.commit().await?;
+
+        Ok(count)
+    }
+
+    async fn bundle(&self, tenant_id: &str, locale: &str, namespace: Option<&str>) -> WhiteLabelResult<TranslationBundle> {
+        let mut namespaces: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+        for entry in self.list(tenant_id, Some(locale), namespace).await? {
+            namespaces.entry(entry.namespace).or_default().insert(entry.key, entry.value);
+        }
+
+        Ok(TranslationBundle {
+            locale: locale.to_string(),
+            namespaces,
+        })
+    }
+}
+
+/// Locales a locale's strings are looked up in, most specific first
+pub fn fallback_chain(locale: &str, settings: &TranslationSettings) -> Vec<String> {
+    let mut chain: Vec<String> = Vec::new();
+    let language = locale.split('-').next().unwrap_or(locale);
+
+    let configured = |locale: &str| settings.fallbacks.get(locale).cloned().unwrap_or_default();
+    let candidates = std::iter::once(locale.to_string())
+        .chain(configured(locale))
+        .chain(std::iter::once(language.to_string()))
+        .chain(configured(language))
+        .chain(std::iter::once(settings.default_locale.clone()));
+
+    for candidate in candidates {
+        if !candidate.is_empty() && !chain.contains(&candidate) {
+            chain.push(candidate);
+        }
+    }
+    chain
+}
+
+async fn bump_revision(tx: &mut sqlx::Transaction<'_, sqlx::Postgres>, tenant_id: &str) -> WhiteLabelResult<()> {
+    sqlx::query!(
+        r#"
+        INSERT INTO translation_settings (tenant_id, revision) VALUES ($1, 1)
+        ON CONFLICT (tenant_id) DO UPDATE
+        SET revision = translation_settings.revision + 1, updated_at = NOW()
+        "#,
+        tenant_id
+    )
+    .execute(&mut **tx)
+    .await?;
+    Ok(())
+}
+
+fn required_locale(locale: &str) -> WhiteLabelResult<String> {
+    let locale = normalize_locale(Some(locale))?;
+    if locale.is_empty() {
+        return Err(WhiteLabelError::Validation("A locale is required".to_string()));
+    }
+    Ok(locale)
+}
+
+fn validate_namespace(namespace: &str) -> WhiteLabelResult<()> {
+    let valid = !namespace.is_empty()
+        && namespace.len() <= 64
+        && namespace
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+    if valid {
+        Ok(())
+    } else {
+        Err(WhiteLabelError::Validation(format!("Invalid namespace {}", namespace)))
+    }
+}
+
+fn validate_string(key: &str, value: Option<&str>) -> WhiteLabelResult<()> {
+    let valid_key = !key.is_empty()
+        && key.len() <= MAX_KEY_LENGTH
+        && key
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | ':'));
+    if !valid_key {
+        return Err(WhiteLabelError::Validation(format!("Invalid translation key {}", key)));
+    }
+    if value.is_some_and(|value| value.len() > MAX_VALUE_BYTES) {
+        return Err(WhiteLabelError::Validation(format!(
+            "Translation of {} exceeds {} KB",
+            key,
+            MAX_VALUE_BYTES / 1024
+        )));
+    }
+    Ok(())
+}
+
+/// XLIFF 1.2 with a `<file>` per namespace of either locale
+fn to_xliff(sources: &TranslationBundle, targets: &TranslationBundle) -> String {
+    let empty = BTreeMap::new();
+    let namespaces: BTreeSet<&String> = sources.namespaces.keys().chain(targets.namespaces.keys()).collect();
+
+    let mut xliff = String::from(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<xliff version=\"1.2\" xmlns=\"urn:oasis:names:tc:xliff:document:1.2\">\n",
+    );
+    for namespace in namespaces {
+        let source_strings = sources.namespaces.get(namespace).unwrap_or(&empty);
+        let target_strings = targets.namespaces.get(namespace).unwrap_or(&empty);
+        let keys: BTreeSet<&String> = source_strings.keys().chain(target_strings.keys()).collect();
+
+        xliff.push_str(&format!(
+            "  <file original=\"{}\" source-language=\"{}\" target-language=\"{}\" datatype=\"plaintext\">\n    <body>\n",
+            xml_escape(namespace),
+            xml_escape(&sources.locale),
+            xml_escape(&targets.locale)
+        ));
+        for key in keys {
+            xliff.push_str(&format!("      <trans-unit id=\"{}\">\n", xml_escape(key)));
+            xliff.push_str(&format!(
+                "        <source>{}</source>\n",
+                xml_escape(source_strings.get(key).map(String::as_str).unwrap_or_default())
+            ));
+            if let Some(target) = target_strings.get(key) {
+                xliff.push_str(&format!("        <target>{}</target>\n", xml_escape(target)));
+            }
+            xliff.push_str("      </trans-unit>\n");
+        }
+        xliff.push_str("    </body>\n  </file>\n");
+    }
+    xliff.push_str("</xliff>\n");
+    xliff
+}
+
+/// The translated units of an XLIFF 1.2 document. Units without a target,
+/// or with an empty one, are left out.
+fn parse_xliff(document: &str) -> WhiteLabelResult<TranslationBundle> {
+    let mut reader = Reader::from_str(document);
+    let mut locale: Option<String> = None;
+    let mut namespaces: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+    let mut namespace: Option<String> = None;
+    let mut unit: Option<String> = None;
+    // The target's text so far, while inside one
+    let mut target: Option<String> = None;
+
+    loop {
+        match reader.read_event().map_err(invalid_xliff)? {
+            Event::Start(element) => match element.local_name().as_ref() {
+                b"file" => {
+                    let original = attribute(&element, b"original")?
+                        .ok_or_else(|| WhiteLabelError::Validation("XLIFF file without an original".to_string()))?;
+                    validate_namespace(&original)?;
+                    if let Some(language) = attribute(&element, b"target-language")? {
+                        if locale.as_ref().is_some_and(|locale| locale != &language) {
+                            return Err(WhiteLabelError::Validation(
+                                "XLIFF files must share one target language".to_string(),
+                            ));
+                        }
+                        locale = Some(language);
+                    }
+                    namespace = Some(original);
+                }
+                b"trans-unit" => {
+                    let id = attribute(&element, b"id")?
+                        .ok_or_else(|| WhiteLabelError::Validation("XLIFF unit without an id".to_string()))?;
+                    unit = Some(id);
+                }
+                b"target" if unit.is_some() => target = Some(String::new()),
+                _ => {}
+            },
+            Event::Text(text) => {
+                if let Some(target) = target.as_mut() {
+                    target.push_str(&text.unescape().map_err(invalid_xliff)?);
+                }
+            }
+            Event::CData(data) => {
+                if let Some(target) = target.as_mut() {
+                    target.push_str(&String::from_utf8_lossy(&data));
+                }
+            }
+            Event::End(element) => match element.local_name().as_ref() {
+                b"target" => {
+                    if let (Some(namespace), Some(unit), Some(value)) = (&namespace, &unit, target.take()) {
+                        if !value.trim().is_empty() {
+                            namespaces
+                                .entry(namespace.clone())
+                                .or_default()
+                                .insert(unit.clone(), value);
+                        }
+                    }
+                }
+                b"trans-unit" => unit = None,
+                b"file" => namespace = None,
+                _ => {}
+            },
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(TranslationBundle {
+        locale: locale.unwrap_or_default(),
+        namespaces,
+    })
+}
+
+fn attribute(element: &BytesStart, name: &[u8]) -> WhiteLabelResult<Option<String>> {
+    match element.try_get_attribute(name).map_err(invalid_xliff)? {
+        Some(attribute) => Ok(Some(attribute.unescape_value().map_err(invalid_xliff)?.into_owned())),
+        None => Ok(None),
+    }
+}
+
+fn invalid_xliff(error: impl std::fmt::Display) -> WhiteLabelError {
+    WhiteLabelError::Validation(format!("Invalid XLIFF: {}", error))
+}
+
+fn xml_escape(value: &str) -> String {
+    value
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
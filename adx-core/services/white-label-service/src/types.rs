@@ -1,6 +1,9 @@
+use adx_shared::email::SystemEmail;
+use adx_shared::login_page::{AuthPage, LoginPageConfig};
 use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
 use serde::{Deserialize, Serialize};
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use uuid::Uuid;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -192,6 +195,378 @@ pub struct Typography {
     pub font_sizes: FontSizes,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BrandingVersionStatus {
+    Draft,
+    Published,
+    Archived,
+}
+
+/// One version of a tenant's theme
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrandingVersion {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub version: i32,
+    pub status: BrandingVersionStatus,
+    pub theme: BrandingTheme,
+    pub created_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub published_by: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+/// The editable part of a theme. Logo and favicon are uploaded branding assets.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BrandingTheme {
+    pub brand_name: String,
+    pub logo_asset_id: Option<Uuid>,
+    pub favicon_asset_id: Option<Uuid>,
+    pub color_scheme: ColorScheme,
+    pub typography: Typography,
+    pub custom_css: Option<String>,
+}
+
+/// A domain's published theme, as the frontend shell loads it at boot
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishedBranding {
+    pub version: i32,
+    pub brand_name: String,
+    pub logo_url: Option<String>,
+    pub favicon_url: Option<String>,
+    pub color_scheme: ColorScheme,
+    pub typography: Typography,
+    pub custom_css: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailTemplateFormat {
+    /// MJML markup, compiled to responsive HTML. Bodies without an `<mjml>`
+    /// root are placed in the branded layout.
+    Mjml,
+    /// HTML sent as written
+    Handlebars,
+}
+
+/// A system email template. Subject, body and text body are Handlebars
+/// templates.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailTemplateContent {
+    pub format: EmailTemplateFormat,
+    pub subject: String,
+    pub body: String,
+    /// Plain-text alternative; the platform default's is used when missing
+    pub text_body: Option<String>,
+}
+
+/// A tenant's override of a system email
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailTemplateOverride {
+    pub id: Uuid,
+    pub tenant_id: String,
+    pub template: SystemEmail,
+    /// None for the override used when no locale matches
+    pub locale: Option<String>,
+    pub content: EmailTemplateContent,
+    pub enabled: bool,
+    pub updated_by: Option<String>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A system email with the variables it is rendered with and the tenant's
+/// overrides of it
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EmailTemplateSummary {
+    pub template: SystemEmail,
+    pub variables: Vec<String>,
+    pub overrides: Vec<EmailTemplateOverride>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SaveEmailTemplateRequest {
+    pub locale: Option<String>,
+    #[serde(flatten)]
+    pub content: EmailTemplateContent,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct PreviewEmailTemplateRequest {
+    pub locale: Option<String>,
+    /// Sample values; missing variables render as `[name]`
+    #[serde(default)]
+    pub variables: serde_json::Map<String, serde_json::Value>,
+    /// Unsaved content to preview instead of the stored template
+    pub content: Option<EmailTemplateContent>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub html_body: String,
+    pub text_body: String,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum EmailProvider {
+    Smtp,
+    Ses,
+}
+
+impl EmailProvider {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EmailProvider::Smtp => "smtp",
+            EmailProvider::Ses => "ses",
+        }
+    }
+}
+
+/// A tenant's own mail delivery. Passwords and secret keys are write-only.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantEmailSettings {
+    pub tenant_id: String,
+    pub provider: EmailProvider,
+    pub from_email: String,
+    pub from_name: Option<String>,
+    pub reply_to: Option<String>,
+    pub smtp_host: Option<String>,
+    pub smtp_port: Option<u16>,
+    pub smtp_username: Option<String>,
+    pub smtp_starttls: bool,
+    pub ses_region: Option<String>,
+    pub ses_access_key_id: Option<String>,
+    pub ses_configuration_set: Option<String>,
+    pub has_secret: bool,
+    pub updated_by: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Omitted secrets keep their stored value
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateEmailSettingsRequest {
+    pub provider: EmailProvider,
+    pub from_email: String,
+    pub from_name: Option<String>,
+    pub reply_to: Option<String>,
+    pub smtp_host: Option<String>,
+    pub smtp_port: Option<u16>,
+    pub smtp_username: Option<String>,
+    pub smtp_password: Option<String>,
+    pub smtp_starttls: Option<bool>,
+    pub ses_region: Option<String>,
+    pub ses_access_key_id: Option<String>,
+    pub ses_secret_access_key: Option<String>,
+    pub ses_configuration_set: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TestEmailRequest {
+    pub to: String,
+}
+
+/// A tenant's hosted login page configuration: the draft being edited and
+/// the version auth-service serves
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LoginPageSettings {
+    pub tenant_id: String,
+    pub draft: Option<LoginPageConfig>,
+    pub draft_updated_by: Option<String>,
+    pub draft_updated_at: Option<DateTime<Utc>>,
+    pub published: Option<LoginPageConfig>,
+    pub version: i32,
+    pub published_by: Option<String>,
+    pub published_at: Option<DateTime<Utc>>,
+}
+
+/// Render a login page configuration as it would be served. Without a
+/// config the draft, or else the published configuration, is rendered.
+#[derive(Debug, Clone, Deserialize)]
+pub struct PreviewLoginPageRequest {
+    pub config: Option<LoginPageConfig>,
+    #[serde(default = "default_preview_page")]
+    pub page: AuthPage,
+    pub locale: Option<String>,
+}
+
+fn default_preview_page() -> AuthPage {
+    AuthPage::Login
+}
+
+/// A customer tenant owned by a reseller
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResellerTenant {
+    pub reseller_id: Uuid,
+    pub tenant_id: String,
+    pub added_by: Option<String>,
+    pub added_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct AssignResellerTenantRequest {
+    pub tenant_id: String,
+}
+
+/// A customer's themes as its reseller sees them. Until the customer
+/// publishes a theme of its own it is served the closest published theme up
+/// its reseller chain.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CustomerBranding {
+    pub tenant_id: String,
+    pub draft: Option<BrandingVersion>,
+    pub published: Option<BrandingVersion>,
+    /// The theme served to the customer, which belongs to a reseller when
+    /// `inherited`
+    pub effective: Option<BrandingVersion>,
+    pub inherited: bool,
+}
+
+/// Licenses, billing and quota usage across a reseller's customers, as
+/// reported by license-service
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UsageRollupReport {
+    pub period_start: DateTime<Utc>,
+    pub period_end: DateTime<Utc>,
+    pub tenants: Vec<TenantUsageRollup>,
+    pub totals: Vec<CurrencyBillingTotal>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TenantUsageRollup {
+    pub tenant_id: Uuid,
+    pub subscription_tier: Option<String>,
+    pub license_status: Option<String>,
+    pub billing: Vec<CurrencyBillingTotal>,
+    pub quotas: Vec<QuotaUsage>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CurrencyBillingTotal {
+    pub currency: String,
+    pub billed: Decimal,
+    pub paid: Decimal,
+    pub outstanding: Decimal,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct QuotaUsage {
+    pub quota_name: String,
+    pub current_usage: i64,
+    pub quota_limit: i64,
+    pub usage_percentage: f64,
+    pub warning_threshold_reached: bool,
+    pub is_exceeded: bool,
+    pub unit: String,
+    pub category: String,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UsageRollupQuery {
+    pub start_date: Option<DateTime<Utc>>,
+    pub end_date: Option<DateTime<Utc>>,
+}
+
+/// How a tenant's locales fall back. A locale falls back to its configured
+/// fallbacks, then to its language and that language's fallbacks, then to
+/// the default locale.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranslationSettings {
+    pub tenant_id: String,
+    pub default_locale: String,
+    pub fallbacks: HashMap<String, Vec<String>>,
+    pub revision: i64,
+    pub updated_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct UpdateTranslationSettingsRequest {
+    pub default_locale: String,
+    #[serde(default)]
+    pub fallbacks: HashMap<String, Vec<String>>,
+}
+
+/// A tenant's string for a key in a locale's namespace
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranslationOverride {
+    pub locale: String,
+    pub namespace: String,
+    pub key: String,
+    pub value: String,
+    pub updated_by: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TranslationQuery {
+    pub locale: Option<String>,
+    pub namespace: Option<String>,
+}
+
+/// Strings to set in a locale's namespace; a null value removes the
+/// tenant's string for that key
+#[derive(Debug, Clone, Deserialize)]
+pub struct SaveTranslationsRequest {
+    pub strings: BTreeMap<String, Option<String>>,
+}
+
+/// A namespace's strings as the tenant's users get them in a locale
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ResolvedTranslations {
+    pub locale: String,
+    pub namespace: String,
+    /// Locales the strings were drawn from, most specific first
+    pub fallback_chain: Vec<String>,
+    pub revision: i64,
+    pub strings: BTreeMap<String, String>,
+}
+
+/// Exchange formats for translation agencies
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TranslationFormat {
+    /// XLIFF 1.2, one `<file>` per namespace
+    Xliff,
+    /// A `TranslationBundle`
+    Json,
+}
+
+/// One locale's strings by namespace and key
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranslationBundle {
+    pub locale: String,
+    pub namespaces: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TranslationExportQuery {
+    /// Locale to translate into
+    pub locale: String,
+    /// Locale whose strings are the XLIFF sources; the default locale if unset
+    pub source_locale: Option<String>,
+    pub namespace: Option<String>,
+    pub format: Option<TranslationFormat>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct TranslationImportQuery {
+    pub format: Option<TranslationFormat>,
+    /// Overrides the locale named in the document
+    pub locale: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TranslationImportResult {
+    pub locale: String,
+    pub namespaces: Vec<String>,
+    pub imported: usize,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct FontSizes {
     pub small: String,
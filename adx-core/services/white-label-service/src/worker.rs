@@ -1,9 +1,11 @@
 use crate::activities::WhiteLabelActivities;
 use crate::config::WhiteLabelConfig;
 use crate::services::{AssetService, DnsService, EmailService, SslService, StorageService};
+use crate::workflows::{certificate_renewal_workflow, domain_certificate_workflow};
 use crate::workflows_simple::{
     custom_domain_setup_workflow, reseller_setup_workflow, white_label_branding_workflow,
 };
+use adx_shared::database::encryption::FieldCipher;
 use sqlx::PgPool;
 use std::sync::Arc;
 use crate::temporal_mock::{Worker, WorkerBuilder};
@@ -22,8 +24,9 @@ impl WhiteLabelWorker {
         tracing::info!("Starting White Label Service Temporal worker");
 
         // Create services
+        let cipher = Arc::new(FieldCipher::from_env()?);
         let dns_service = Arc::new(DnsService::new(self.config.clone())?);
-        let ssl_service = Arc::new(SslService::new(self.config.clone()));
+        let ssl_service = Arc::new(SslService::new(self.config.clone(), self.db_pool.clone(), cipher.clone()));
         let asset_service = Arc::new(AssetService::new(self.config.clone()));
         let email_service = Arc::new(EmailService::new(self.config.clone())?);
         let storage_service = Arc::new(StorageService::new(self.config.clone()));
@@ -37,6 +40,7 @@ impl WhiteLabelWorker {
             asset_service,
             email_service,
             storage_service,
+            cipher,
         ));
 
         // Create worker
@@ -47,6 +51,8 @@ impl WhiteLabelWorker {
 
         // Register workflows
         worker.register_workflow(custom_domain_setup_workflow).await?;
+        worker.register_workflow(domain_certificate_workflow).await?;
+        worker.register_workflow(certificate_renewal_workflow).await?;
         worker.register_workflow(white_label_branding_workflow).await?;
         worker.register_workflow(reseller_setup_workflow).await?;
 
@@ -55,8 +61,11 @@ impl WhiteLabelWorker {
         worker.register_activity(activities.create_domain_record).await?;
         worker.register_activity(activities.generate_dns_verification_records).await?;
         worker.register_activity(activities.verify_dns_records).await?;
+        worker.register_activity(activities.record_domain_verification).await?;
         worker.register_activity(activities.update_domain_status).await?;
         worker.register_activity(activities.provision_ssl_certificate).await?;
+        worker.register_activity(activities.list_certificates_due_for_renewal).await?;
+        worker.register_activity(activities.renew_ssl_certificate).await?;
         worker.register_activity(activities.configure_domain_routing).await?;
 
         worker.register_activity(activities.validate_branding_request).await?;
@@ -13,11 +13,11 @@ pub async fn custom_domain_setup_workflow(
     request: CustomDomainSetupRequest,
 ) -> Result<CustomDomainSetupResult, WhiteLabelError> {
     // Step 1: Validate domain and create domain record
-    // Mock activity call - in real implementation would use Temporal activity execution
-    let validation_result = DomainValidationResult {
-        is_valid: true,
-        error_message: None,
-    };
+    let validation_result = ctx
+        .activity(ActivityOptions::default())
+        .call(WhiteLabelActivities::validate_domain, request.clone())
+        .await
+        .map_err(|e| WhiteLabelError::Temporal(e.to_string()))?;
 
     if !validation_result.is_valid {
         return Err(WhiteLabelError::DomainValidation(
@@ -52,7 +52,7 @@ pub async fn custom_domain_setup_workflow(
     // Step 5: Verify DNS records
     let mut verification_attempts = 0;
     let max_attempts = 10;
-    let mut verification_result = None;
+    let mut verified = false;
 
     while verification_attempts < max_attempts {
         let result = ctx
@@ -67,8 +67,19 @@ pub async fn custom_domain_setup_workflow(
             .await
             .map_err(|e| WhiteLabelError::Temporal(e.to_string()))?;
 
+        ctx.activity(ActivityOptions::default())
+            .call(
+                WhiteLabelActivities::record_domain_verification,
+                RecordDomainVerificationRequest {
+                    domain_id: domain_record.id,
+                    error_message: result.error_message.clone(),
+                },
+            )
+            .await
+            .map_err(|e| WhiteLabelError::Temporal(e.to_string()))?;
+
         if result.verified {
-            verification_result = Some(result);
+            verified = true;
             break;
         }
 
@@ -78,8 +89,21 @@ pub async fn custom_domain_setup_workflow(
         }
     }
 
-    let verification_result = verification_result
-        .ok_or_else(|| WhiteLabelError::DnsVerification("DNS verification timeout".to_string()))?;
+    if !verified {
+        ctx.activity(ActivityOptions::default())
+            .call(
+                WhiteLabelActivities::update_domain_status,
+                UpdateDomainStatusRequest {
+                    domain_id: domain_record.id,
+                    status: DomainStatus::Failed,
+                    verified_at: None,
+                },
+            )
+            .await
+            .map_err(|e| WhiteLabelError::Temporal(e.to_string()))?;
+
+        return Err(WhiteLabelError::DnsVerification("DNS verification timeout".to_string()));
+    }
 
     // Step 6: Update domain status to verified
     ctx.activity(ActivityOptions::default())
@@ -103,6 +127,7 @@ pub async fn custom_domain_setup_workflow(
                 ProvisionSslRequest {
                     domain: request.domain.clone(),
                     domain_id: domain_record.id,
+                    tenant_id: request.tenant_id.clone(),
                 },
             )
             .await
@@ -136,6 +161,77 @@ pub async fn custom_domain_setup_workflow(
     })
 }
 
+/// Issue a certificate for an already verified domain and route it with TLS
+// #[workflow] - would use temporal workflow attribute when available
+pub async fn domain_certificate_workflow(
+    ctx: WfContext,
+    request: DomainCertificateRequest,
+) -> Result<SslCertificateResult, WhiteLabelError> {
+    let ssl_result = ctx
+        .activity(ActivityOptions::default())
+        .call(
+            WhiteLabelActivities::provision_ssl_certificate,
+            ProvisionSslRequest {
+                domain: request.domain.clone(),
+                domain_id: request.domain_id,
+                tenant_id: request.tenant_id.clone(),
+            },
+        )
+        .await
+        .map_err(|e| WhiteLabelError::Temporal(e.to_string()))?;
+
+    ctx.activity(ActivityOptions::default())
+        .call(
+            WhiteLabelActivities::configure_domain_routing,
+            ConfigureDomainRoutingRequest {
+                domain: request.domain,
+                tenant_id: request.tenant_id,
+                ssl_certificate_id: Some(ssl_result.certificate_id.clone()),
+                auto_redirect: request.auto_redirect,
+            },
+        )
+        .await
+        .map_err(|e| WhiteLabelError::Temporal(e.to_string()))?;
+
+    Ok(ssl_result)
+}
+
+/// Renew certificates nearing expiry, once a day. A failed renewal is
+/// recorded on the certificate and retried on the next pass while the
+/// current certificate keeps serving.
+// #[workflow] - would use temporal workflow attribute when available
+pub async fn certificate_renewal_workflow(
+    ctx: WfContext,
+    request: CertificateRenewalRequest,
+) -> Result<(), WhiteLabelError> {
+    loop {
+        let due = ctx
+            .activity(ActivityOptions::default())
+            .call(
+                WhiteLabelActivities::list_certificates_due_for_renewal,
+                ListCertificateRenewalsRequest {
+                    renewal_days_before_expiry: request.renewal_days_before_expiry,
+                    limit: request.batch_size,
+                },
+            )
+            .await
+            .map_err(|e| WhiteLabelError::Temporal(e.to_string()))?;
+
+        for target in due {
+            let domain = target.domain.clone();
+            if let Err(e) = ctx
+                .activity(ActivityOptions::default())
+                .call(WhiteLabelActivities::renew_ssl_certificate, target)
+                .await
+            {
+                tracing::warn!("Certificate renewal failed for {}: {}", domain, e);
+            }
+        }
+
+        ctx.sleep(Duration::days(1)).await;
+    }
+}
+
 /// White-label branding workflow with asset validation and rollback capability
 // #[workflow] - would use temporal workflow attribute when available
 pub async fn white_label_branding_workflow(
@@ -526,6 +622,12 @@ pub struct VerifyDnsRequest {
     pub expected_records: Vec<DnsRecord>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RecordDomainVerificationRequest {
+    pub domain_id: Uuid,
+    pub error_message: Option<String>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UpdateDomainStatusRequest {
     pub domain_id: Uuid,
@@ -537,6 +639,27 @@ pub struct UpdateDomainStatusRequest {
 pub struct ProvisionSslRequest {
     pub domain: String,
     pub domain_id: Uuid,
+    pub tenant_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DomainCertificateRequest {
+    pub domain_id: Uuid,
+    pub tenant_id: String,
+    pub domain: String,
+    pub auto_redirect: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CertificateRenewalRequest {
+    pub renewal_days_before_expiry: u32,
+    pub batch_size: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListCertificateRenewalsRequest {
+    pub renewal_days_before_expiry: u32,
+    pub limit: u32,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
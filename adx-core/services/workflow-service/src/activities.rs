@@ -2,6 +2,8 @@ use crate::{
     error::{WorkflowServiceError, WorkflowServiceResult},
     models::*,
     config::WorkflowServiceConfig,
+    controls::RunControl,
+    monitoring::RunCostMeter,
 };
 use async_trait::async_trait;
 use chrono::{DateTime, Utc};
@@ -9,7 +11,8 @@ use reqwest::Client;
 use serde::{Deserialize, Serialize};
 use serde_json::{json, Value};
 use std::collections::HashMap;
-use std::time::Duration;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
 use tracing::{info, warn, error};
 
 #[async_trait]
@@ -19,6 +22,7 @@ pub trait CrossServiceActivities: Send + Sync {
     async fn validate_user_credentials(&self, request: ValidateUserCredentialsRequest) -> WorkflowServiceResult<ValidateUserCredentialsResult>;
     async fn update_user_session(&self, request: UpdateUserSessionRequest) -> WorkflowServiceResult<UpdateUserSessionResult>;
     async fn revoke_user_sessions(&self, request: RevokeUserSessionsRequest) -> WorkflowServiceResult<RevokeUserSessionsResult>;
+    async fn delete_user_account(&self, request: DeleteUserAccountRequest) -> WorkflowServiceResult<DeleteUserAccountResult>;
 
     // User Service Activities
     async fn create_user_profile(&self, request: CreateUserProfileRequest) -> WorkflowServiceResult<CreateUserProfileResult>;
@@ -31,6 +35,8 @@ pub trait CrossServiceActivities: Send + Sync {
     async fn get_tenant_context(&self, request: GetTenantContextRequest) -> WorkflowServiceResult<GetTenantContextResult>;
     async fn update_tenant_user_membership(&self, request: UpdateTenantUserMembershipRequest) -> WorkflowServiceResult<UpdateTenantUserMembershipResult>;
     async fn get_tenant_data_for_migration(&self, request: GetTenantDataRequest) -> WorkflowServiceResult<GetTenantDataResult>;
+    async fn create_tenant(&self, request: CreateTenantRequest) -> WorkflowServiceResult<CreateTenantResult>;
+    async fn delete_tenant(&self, request: DeleteTenantRequest) -> WorkflowServiceResult<DeleteTenantResult>;
 
     // File Service Activities
     async fn setup_user_file_workspace(&self, request: SetupUserFileWorkspaceRequest) -> WorkflowServiceResult<SetupUserFileWorkspaceResult>;
@@ -38,16 +44,31 @@ pub trait CrossServiceActivities: Send + Sync {
     async fn export_user_files(&self, request: ExportUserFilesRequest) -> WorkflowServiceResult<ExportUserFilesResult>;
     async fn delete_user_files(&self, request: DeleteUserFilesRequest) -> WorkflowServiceResult<DeleteUserFilesResult>;
 
+    // License Service Activities
+    async fn provision_license(&self, request: ProvisionLicenseRequest) -> WorkflowServiceResult<ProvisionLicenseResult>;
+    async fn revoke_license(&self, request: RevokeLicenseRequest) -> WorkflowServiceResult<RevokeLicenseResult>;
+
+    // Module Service Activities
+    async fn run_module_job(&self, request: ModuleJobRequest) -> WorkflowServiceResult<ModuleJobResult>;
+
     // Cross-Service Coordination Activities
     async fn coordinate_service_health_check(&self, services: Vec<String>) -> WorkflowServiceResult<ServiceHealthCheckResult>;
     async fn create_cross_service_backup(&self, request: CreateBackupRequest) -> WorkflowServiceResult<CreateBackupResult>;
     async fn restore_from_backup(&self, request: RestoreBackupRequest) -> WorkflowServiceResult<RestoreBackupResult>;
     async fn send_notification(&self, request: SendNotificationRequest) -> WorkflowServiceResult<SendNotificationResult>;
+
+    /// Called by workflows between units of work; waits while the run is
+    /// paused and fails once it has been cancelled
+    async fn checkpoint(&self) -> WorkflowServiceResult<()> {
+        Ok(())
+    }
 }
 
 pub struct CrossServiceActivitiesImpl {
     config: WorkflowServiceConfig,
     http_client: Client,
+    meter: Option<Arc<RunCostMeter>>,
+    control: Option<RunControl>,
 }
 
 impl CrossServiceActivitiesImpl {
@@ -60,9 +81,23 @@ impl CrossServiceActivitiesImpl {
         Self {
             config,
             http_client,
+            meter: None,
+            control: None,
         }
     }
 
+    /// Record every downstream call made for the run on `meter`
+    pub fn with_meter(mut self, meter: Arc<RunCostMeter>) -> Self {
+        self.meter = Some(meter);
+        self
+    }
+
+    /// Honour pause and cancel requests made through `control`
+    pub fn with_control(mut self, control: RunControl) -> Self {
+        self.control = Some(control);
+        self
+    }
+
     async fn call_service<T: serde::de::DeserializeOwned>(
         &self,
         service_url: &str,
@@ -71,6 +106,29 @@ impl CrossServiceActivitiesImpl {
         payload: Option<Value>,
         tenant_id: &str,
         user_id: Option<&str>,
+    ) -> WorkflowServiceResult<T> {
+        let started = Instant::now();
+        let result = self.send_request(service_url, endpoint, method, payload, tenant_id, user_id).await;
+
+        if let Some(meter) = &self.meter {
+            meter.record_call(
+                self.config.services.service_name(service_url),
+                &format!("{} {}", method, endpoint),
+                started.elapsed(),
+                result.is_ok(),
+            );
+        }
+        result
+    }
+
+    async fn send_request<T: serde::de::DeserializeOwned>(
+        &self,
+        service_url: &str,
+        endpoint: &str,
+        method: &str,
+        payload: Option<Value>,
+        tenant_id: &str,
+        user_id: Option<&str>,
     ) -> WorkflowServiceResult<T> {
         let url = format!("{}{}", service_url, endpoint);
         let mut request = match method {
@@ -103,6 +161,10 @@ impl CrossServiceActivitiesImpl {
             });
         }
 
+        if response.status() == reqwest::StatusCode::NO_CONTENT {
+            return Ok(serde_json::from_value(Value::Null)?);
+        }
+
         let result = response.json::<T>().await?;
         Ok(result)
     }
@@ -184,6 +246,23 @@ impl CrossServiceActivities for CrossServiceActivitiesImpl {
         Ok(result)
     }
 
+    async fn delete_user_account(&self, request: DeleteUserAccountRequest) -> WorkflowServiceResult<DeleteUserAccountResult> {
+        info!("Deleting user account: {}", request.user_id);
+
+        self.call_service::<Option<Value>>(
+            &self.config.services.auth_service,
+            &format!("/api/v1/users/{}", request.user_id),
+            "DELETE",
+            None,
+            &request.tenant_id,
+            None,
+        ).await?;
+
+        Ok(DeleteUserAccountResult {
+            deleted_at: Utc::now(),
+        })
+    }
+
     async fn create_user_profile(&self, request: CreateUserProfileRequest) -> WorkflowServiceResult<CreateUserProfileResult> {
         info!("Creating user profile for user: {}", request.user_id);
         
@@ -325,6 +404,54 @@ impl CrossServiceActivities for CrossServiceActivitiesImpl {
         Ok(result)
     }
 
+    async fn create_tenant(&self, request: CreateTenantRequest) -> WorkflowServiceResult<CreateTenantResult> {
+        info!("Creating tenant: {}", request.name);
+
+        let payload = json!({
+            "name": request.name,
+            "admin_email": request.admin_email,
+            "subscription_tier": request.subscription_tier,
+            "features": request.features
+        });
+
+        let tenant = self.call_service::<Value>(
+            &self.config.services.tenant_service,
+            "/api/v1/tenants",
+            "POST",
+            Some(payload),
+            "system",
+            None,
+        ).await?;
+
+        let tenant_id = tenant["id"].as_str().ok_or_else(|| WorkflowServiceError::ServiceCommunication {
+            service: self.config.services.tenant_service.clone(),
+            message: "Created tenant has no id".to_string(),
+        })?;
+
+        info!("Tenant created with ID: {}", tenant_id);
+        Ok(CreateTenantResult {
+            tenant_id: tenant_id.to_string(),
+            created_at: Utc::now(),
+        })
+    }
+
+    async fn delete_tenant(&self, request: DeleteTenantRequest) -> WorkflowServiceResult<DeleteTenantResult> {
+        info!("Deleting tenant: {}", request.tenant_id);
+
+        self.call_service::<Option<Value>>(
+            &self.config.services.tenant_service,
+            &format!("/api/v1/tenants/{}", request.tenant_id),
+            "DELETE",
+            None,
+            &request.tenant_id,
+            None,
+        ).await?;
+
+        Ok(DeleteTenantResult {
+            deleted_at: Utc::now(),
+        })
+    }
+
     async fn setup_user_file_workspace(&self, request: SetupUserFileWorkspaceRequest) -> WorkflowServiceResult<SetupUserFileWorkspaceResult> {
         info!("Setting up file workspace for user: {}", request.user_id);
         
@@ -400,6 +527,91 @@ impl CrossServiceActivities for CrossServiceActivitiesImpl {
         Ok(result)
     }
 
+    async fn provision_license(&self, request: ProvisionLicenseRequest) -> WorkflowServiceResult<ProvisionLicenseResult> {
+        info!("Provisioning {} license for tenant: {}", request.subscription_tier, request.tenant_id);
+
+        let payload = json!({
+            "tenant_id": request.tenant_id,
+            "subscription_tier": request.subscription_tier,
+            "billing_cycle": request.billing_cycle,
+            "base_price": request.base_price,
+            "currency": request.currency,
+            "features": request.features,
+            "custom_quotas": null,
+            "auto_renew": true
+        });
+
+        let response = self.call_service::<Value>(
+            &self.config.services.license_service,
+            "/licenses",
+            "POST",
+            Some(payload),
+            &request.tenant_id,
+            None,
+        ).await?;
+
+        let license_id = response["data"]["id"].as_str().ok_or_else(|| WorkflowServiceError::ServiceCommunication {
+            service: self.config.services.license_service.clone(),
+            message: "Created license has no id".to_string(),
+        })?;
+
+        info!("License provisioned with ID: {}", license_id);
+        Ok(ProvisionLicenseResult {
+            license_id: license_id.to_string(),
+            created_at: Utc::now(),
+        })
+    }
+
+    async fn revoke_license(&self, request: RevokeLicenseRequest) -> WorkflowServiceResult<RevokeLicenseResult> {
+        info!("Revoking license {} of tenant: {}", request.license_id, request.tenant_id);
+
+        let payload = json!({
+            "status": "Cancelled",
+            "auto_renew": false
+        });
+
+        self.call_service::<Value>(
+            &self.config.services.license_service,
+            &format!("/licenses/{}", request.license_id),
+            "PUT",
+            Some(payload),
+            &request.tenant_id,
+            None,
+        ).await?;
+
+        Ok(RevokeLicenseResult {
+            revoked_at: Utc::now(),
+        })
+    }
+
+    async fn run_module_job(&self, request: ModuleJobRequest) -> WorkflowServiceResult<ModuleJobResult> {
+        info!("Running job {} of module {} for tenant: {}", request.job_name, request.module_id, request.tenant_id);
+
+        let response = self.call_service::<Value>(
+            &self.config.services.module_service,
+            &format!("/api/v1/tenants/{}/jobs/{}/runs", request.tenant_id, request.job_id),
+            "POST",
+            None,
+            &request.tenant_id,
+            None,
+        ).await?;
+
+        let run: ModuleJobRun = serde_json::from_value(response["data"].clone()).map_err(|e| {
+            WorkflowServiceError::ServiceCommunication {
+                service: self.config.services.module_service.clone(),
+                message: format!("Unexpected job run: {}", e),
+            }
+        })?;
+
+        Ok(ModuleJobResult {
+            job_id: request.job_id,
+            run_id: run.id,
+            status: run.status,
+            error: run.error,
+            completed_at: run.finished_at.unwrap_or_else(Utc::now),
+        })
+    }
+
     async fn coordinate_service_health_check(&self, services: Vec<String>) -> WorkflowServiceResult<ServiceHealthCheckResult> {
         info!("Coordinating health check for services: {:?}", services);
         
@@ -411,6 +623,7 @@ impl CrossServiceActivities for CrossServiceActivitiesImpl {
                 "user" => &self.config.services.user_service,
                 "tenant" => &self.config.services.tenant_service,
                 "file" => &self.config.services.file_service,
+                "license" => &self.config.services.license_service,
                 _ => continue,
             };
             
@@ -471,6 +684,13 @@ impl CrossServiceActivities for CrossServiceActivitiesImpl {
             delivery_status: "sent".to_string(),
         })
     }
+
+    async fn checkpoint(&self) -> WorkflowServiceResult<()> {
+        match &self.control {
+            Some(control) => control.checkpoint().await,
+            None => Ok(()),
+        }
+    }
 }
 
 // Activity Request/Result Types
@@ -528,6 +748,17 @@ pub struct RevokeUserSessionsResult {
     pub revoked_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteUserAccountRequest {
+    pub user_id: String,
+    pub tenant_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteUserAccountResult {
+    pub deleted_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct CreateUserProfileRequest {
     pub user_id: String,
@@ -629,6 +860,30 @@ pub struct GetTenantDataResult {
     pub exported_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTenantRequest {
+    pub name: String,
+    pub admin_email: String,
+    pub subscription_tier: String,
+    pub features: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateTenantResult {
+    pub tenant_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteTenantRequest {
+    pub tenant_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeleteTenantResult {
+    pub deleted_at: DateTime<Utc>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct SetupUserFileWorkspaceRequest {
     pub user_id: String,
@@ -682,6 +937,42 @@ pub struct DeleteUserFilesResult {
     pub deleted_at: DateTime<Utc>,
 }
 
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvisionLicenseRequest {
+    pub tenant_id: String,
+    pub subscription_tier: String,
+    pub billing_cycle: String,
+    pub base_price: f64,
+    pub currency: String,
+    pub features: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProvisionLicenseResult {
+    pub license_id: String,
+    pub created_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevokeLicenseRequest {
+    pub tenant_id: String,
+    pub license_id: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RevokeLicenseResult {
+    pub revoked_at: DateTime<Utc>,
+}
+
+/// The part of module-service's job run the workflow reports
+#[derive(Debug, Clone, Deserialize)]
+struct ModuleJobRun {
+    id: uuid::Uuid,
+    status: String,
+    error: Option<String>,
+    finished_at: Option<DateTime<Utc>>,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ServiceHealthCheckResult {
     pub overall_healthy: bool,
@@ -0,0 +1,697 @@
+//! Human approval steps.
+//!
+//! An approval step pauses a template workflow on a task assigned to users
+//! and roles. Assignees see pending tasks in their inbox and approve or
+//! reject them; the decision is recorded and then delivered to the waiting
+//! workflow as the `approval_decision` signal. A policy may widen the
+//! assignees at escalation points and decides what happens when nobody
+//! answers before the deadline.
+
+use crate::{
+    activities::{CrossServiceActivities, SendNotificationRequest},
+    error::{WorkflowServiceError, WorkflowServiceResult},
+    repositories::WorkflowApprovalRepository,
+};
+use adx_shared::temporal::AdxTemporalClient;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::{collections::HashMap, sync::Arc, time::Duration};
+use tokio::sync::{mpsc, Mutex, RwLock};
+use tracing::{info, warn};
+
+/// Signal a decision is delivered to the waiting workflow under
+pub const APPROVAL_SIGNAL: &str = "approval_decision";
+
+const MIN_APPROVAL_TIMEOUT_SECONDS: u64 = 60;
+const MAX_APPROVAL_TIMEOUT_SECONDS: u64 = 30 * 24 * 60 * 60;
+const MAX_ESCALATIONS: usize = 5;
+const MAX_COMMENT_LENGTH: usize = 2000;
+
+fn default_approval_timeout_seconds() -> u64 {
+    7 * 24 * 60 * 60
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
+pub struct Assignees {
+    #[serde(default)]
+    pub users: Vec<String>,
+    #[serde(default)]
+    pub roles: Vec<String>,
+}
+
+impl Assignees {
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty() && self.roles.is_empty()
+    }
+
+    pub fn includes(&self, user_id: &str, roles: &[String]) -> bool {
+        self.users.iter().any(|user| user == user_id) || self.roles.iter().any(|role| roles.contains(role))
+    }
+}
+
+/// The `approval` block of an approval step. Assignees may be references,
+/// e.g. `${params.manager_id}`, resolved when the step starts.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApprovalPolicy {
+    pub title: String,
+    #[serde(default)]
+    pub description: String,
+    pub assignees: Assignees,
+    /// Time assignees have to decide, counted from when the step starts
+    #[serde(default = "default_approval_timeout_seconds")]
+    pub timeout_seconds: u64,
+    #[serde(default)]
+    pub on_timeout: TimeoutAction,
+    #[serde(default)]
+    pub on_reject: RejectAction,
+    /// Applied in order while the task is still pending
+    #[serde(default)]
+    pub escalations: Vec<Escalation>,
+    /// Whether the user who started the workflow may decide the task
+    #[serde(default)]
+    pub allow_self_approval: bool,
+}
+
+/// Assignees added to a pending task once `after_seconds` have passed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct Escalation {
+    pub after_seconds: u64,
+    pub assignees: Assignees,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum TimeoutAction {
+    /// Treat silence as a rejection; `on_reject` then applies
+    #[default]
+    Reject,
+    Approve,
+    /// Fail the step outright
+    Fail,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum RejectAction {
+    /// Fail the step, which runs the template's compensation steps
+    #[default]
+    Fail,
+    /// Complete the step with `approved: false` so later steps can branch
+    /// on `${steps.<step_id>.output.approved}`
+    Continue,
+}
+
+impl ApprovalPolicy {
+    pub fn validate(&self, owner: &str, errors: &mut Vec<String>) {
+        if self.title.trim().is_empty() {
+            errors.push(format!("{} approval needs a title", owner));
+        }
+        if self.assignees.is_empty() {
+            errors.push(format!("{} approval needs at least one assignee user or role", owner));
+        }
+        if !(MIN_APPROVAL_TIMEOUT_SECONDS..=MAX_APPROVAL_TIMEOUT_SECONDS).contains(&self.timeout_seconds) {
+            errors.push(format!(
+                "{} approval timeout must be between {} and {} seconds",
+                owner, MIN_APPROVAL_TIMEOUT_SECONDS, MAX_APPROVAL_TIMEOUT_SECONDS
+            ));
+        }
+        if self.escalations.len() > MAX_ESCALATIONS {
+            errors.push(format!("{} approval has more than {} escalations", owner, MAX_ESCALATIONS));
+        }
+
+        let mut previous = 0;
+        for escalation in &self.escalations {
+            if escalation.after_seconds <= previous {
+                errors.push(format!("{} approval escalations must come at increasing times", owner));
+            }
+            if escalation.after_seconds >= self.timeout_seconds {
+                errors.push(format!(
+                    "{} approval escalation after {}s comes after the {}s timeout",
+                    owner, escalation.after_seconds, self.timeout_seconds
+                ));
+            }
+            if escalation.assignees.is_empty() {
+                errors.push(format!("{} approval escalation after {}s has no assignees", owner, escalation.after_seconds));
+            }
+            previous = escalation.after_seconds;
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalStatus {
+    Pending,
+    Approved,
+    Rejected,
+    /// Nobody decided before the deadline
+    Expired,
+}
+
+impl ApprovalStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Self::Pending => "pending",
+            Self::Approved => "approved",
+            Self::Rejected => "rejected",
+            Self::Expired => "expired",
+        }
+    }
+
+    pub fn parse(status: &str) -> WorkflowServiceResult<Self> {
+        match status {
+            "pending" => Ok(Self::Pending),
+            "approved" => Ok(Self::Approved),
+            "rejected" => Ok(Self::Rejected),
+            "expired" => Ok(Self::Expired),
+            other => Err(WorkflowServiceError::Internal(format!("Unknown approval status '{}'", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ApprovalDecision {
+    Approve,
+    Reject,
+}
+
+impl ApprovalDecision {
+    pub fn status(&self) -> ApprovalStatus {
+        match self {
+            Self::Approve => ApprovalStatus::Approved,
+            Self::Reject => ApprovalStatus::Rejected,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalTask {
+    pub approval_id: String,
+    pub tenant_id: String,
+    pub workflow_id: String,
+    pub step_id: String,
+    pub title: String,
+    pub description: String,
+    /// Current assignees, including any added by escalation
+    pub assignees: Assignees,
+    pub requested_by: Option<String>,
+    pub allow_self_approval: bool,
+    /// The step's resolved parameters, shown to assignees
+    pub context: Value,
+    pub status: ApprovalStatus,
+    pub escalation_level: u32,
+    pub escalated_at: Option<DateTime<Utc>>,
+    pub due_at: DateTime<Utc>,
+    pub decided_by: Option<String>,
+    pub comment: Option<String>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Payload of the `approval_decision` signal
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ApprovalSignal {
+    pub approval_id: String,
+    pub decision: ApprovalDecision,
+    pub decided_by: String,
+    pub comment: Option<String>,
+}
+
+/// Output of an approval step
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ApprovalOutcome {
+    pub approval_id: String,
+    pub status: ApprovalStatus,
+    pub approved: bool,
+    pub decided_by: Option<String>,
+    pub comment: Option<String>,
+    pub decided_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateApprovalTaskRequest {
+    pub tenant_id: String,
+    pub workflow_id: String,
+    pub step_id: String,
+    pub requested_by: Option<String>,
+    pub policy: ApprovalPolicy,
+    pub context: Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct EscalateApprovalTaskRequest {
+    pub tenant_id: String,
+    pub approval_id: String,
+    /// 1 for the first escalation
+    pub level: u32,
+    pub assignees: Assignees,
+}
+
+struct ApprovalChannel {
+    sender: mpsc::UnboundedSender<ApprovalSignal>,
+    receiver: Arc<Mutex<mpsc::UnboundedReceiver<ApprovalSignal>>>,
+}
+
+/// Signal channels for pending approval tasks, keyed by approval id. Stands
+/// in for Temporal workflow signals until the service runs on the Temporal
+/// SDK.
+#[derive(Default)]
+pub struct ApprovalSignalRegistry {
+    channels: RwLock<HashMap<String, ApprovalChannel>>,
+}
+
+impl ApprovalSignalRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub async fn open(&self, approval_id: &str) {
+        let mut channels = self.channels.write().await;
+        if channels.contains_key(approval_id) {
+            return;
+        }
+        let (sender, receiver) = mpsc::unbounded_channel();
+        channels.insert(
+            approval_id.to_string(),
+            ApprovalChannel {
+                sender,
+                receiver: Arc::new(Mutex::new(receiver)),
+            },
+        );
+    }
+
+    pub async fn close(&self, approval_id: &str) {
+        self.channels.write().await.remove(approval_id);
+    }
+
+    /// Deliver a decision; returns false when no workflow is waiting on it
+    pub async fn send(&self, signal: ApprovalSignal) -> bool {
+        match self.channels.read().await.get(&signal.approval_id) {
+            Some(channel) => channel.sender.send(signal).is_ok(),
+            None => false,
+        }
+    }
+
+    /// Wait up to `timeout` for the decision
+    pub async fn wait(&self, approval_id: &str, timeout: Duration) -> Option<ApprovalSignal> {
+        let receiver = self.channels.read().await.get(approval_id)?.receiver.clone();
+        let mut receiver = receiver.lock().await;
+
+        tokio::time::timeout(timeout, receiver.recv()).await.ok().flatten()
+    }
+}
+
+#[async_trait]
+pub trait ApprovalActivities: Send + Sync {
+    /// Create the task and notify its assignees. Creating the task again for
+    /// the same workflow step returns the existing one, so retries are safe.
+    async fn create_approval_task(&self, request: CreateApprovalTaskRequest) -> WorkflowServiceResult<ApprovalTask>;
+    async fn get_approval_task(&self, tenant_id: &str, approval_id: &str) -> WorkflowServiceResult<ApprovalTask>;
+    async fn wait_for_approval_signal(&self, approval_id: &str, timeout: Duration) -> Option<ApprovalSignal>;
+    /// Add assignees to a pending task and notify them
+    async fn escalate_approval_task(&self, request: EscalateApprovalTaskRequest) -> WorkflowServiceResult<ApprovalTask>;
+    /// Mark a pending task expired. A task decided in the meantime is
+    /// returned with its decision.
+    async fn expire_approval_task(&self, tenant_id: &str, approval_id: &str) -> WorkflowServiceResult<ApprovalTask>;
+    async fn close_approval_signals(&self, approval_id: &str);
+}
+
+pub struct ApprovalActivitiesImpl {
+    repository: Arc<dyn WorkflowApprovalRepository>,
+    signals: Arc<ApprovalSignalRegistry>,
+    notifier: Arc<dyn CrossServiceActivities>,
+}
+
+impl ApprovalActivitiesImpl {
+    pub fn new(
+        repository: Arc<dyn WorkflowApprovalRepository>,
+        signals: Arc<ApprovalSignalRegistry>,
+        notifier: Arc<dyn CrossServiceActivities>,
+    ) -> Self {
+        Self {
+            repository,
+            signals,
+            notifier,
+        }
+    }
+
+    /// Tell assignees a task is waiting. Roles are addressed as `role:<name>`
+    /// for the notification service to expand. Delivery failures do not
+    /// hold up the workflow.
+    async fn notify(&self, task: &ApprovalTask, assignees: &Assignees, notification_type: &str) {
+        let recipients = assignees
+            .users
+            .iter()
+            .cloned()
+            .chain(assignees.roles.iter().map(|role| format!("role:{}", role)));
+
+        for recipient in recipients {
+            let request = SendNotificationRequest {
+                notification_type: notification_type.to_string(),
+                recipient: recipient.clone(),
+                message: format!("Approval requested: {}", task.title),
+                metadata: HashMap::from([
+                    ("tenant_id".to_string(), task.tenant_id.clone()),
+                    ("approval_id".to_string(), task.approval_id.clone()),
+                    ("workflow_id".to_string(), task.workflow_id.clone()),
+                    ("due_at".to_string(), task.due_at.to_rfc3339()),
+                ]),
+            };
+            if let Err(error) = self.notifier.send_notification(request).await {
+                warn!("Failed to notify {} of approval {}: {}", recipient, task.approval_id, error);
+            }
+        }
+    }
+}
+
+#[async_trait]
+impl ApprovalActivities for ApprovalActivitiesImpl {
+    async fn create_approval_task(&self, request: CreateApprovalTaskRequest) -> WorkflowServiceResult<ApprovalTask> {
+        let (task, created) = self.repository.create_approval(&request).await?;
+        self.signals.open(&task.approval_id).await;
+
+        if created {
+            info!(
+                "Created approval {} for step {} of workflow {}",
+                task.approval_id, task.step_id, task.workflow_id
+            );
+            self.notify(&task, &task.assignees, "approval_requested").await;
+        }
+        Ok(task)
+    }
+
+    async fn get_approval_task(&self, tenant_id: &str, approval_id: &str) -> WorkflowServiceResult<ApprovalTask> {
+        self.repository
+            .get_approval(tenant_id, approval_id)
+            .await?
+            .ok_or_else(|| WorkflowServiceError::NotFound(format!("Approval {} not found", approval_id)))
+    }
+
+    async fn wait_for_approval_signal(&self, approval_id: &str, timeout: Duration) -> Option<ApprovalSignal> {
+        self.signals.wait(approval_id, timeout).await
+    }
+
+    async fn escalate_approval_task(&self, request: EscalateApprovalTaskRequest) -> WorkflowServiceResult<ApprovalTask> {
+        match self
+            .repository
+            .escalate_approval(&request.tenant_id, &request.approval_id, request.level, &request.assignees)
+            .await?
+        {
+            Some(task) => {
+                info!("Escalated approval {} to level {}", task.approval_id, request.level);
+                self.notify(&task, &request.assignees, "approval_escalated").await;
+                Ok(task)
+            }
+            // Decided or already escalated to this level
+            None => self.get_approval_task(&request.tenant_id, &request.approval_id).await,
+        }
+    }
+
+    async fn expire_approval_task(&self, tenant_id: &str, approval_id: &str) -> WorkflowServiceResult<ApprovalTask> {
+        match self.repository.expire_approval(tenant_id, approval_id).await? {
+            Some(task) => Ok(task),
+            None => self.get_approval_task(tenant_id, approval_id).await,
+        }
+    }
+
+    async fn close_approval_signals(&self, approval_id: &str) {
+        self.signals.close(approval_id).await;
+    }
+}
+
+/// Body of an approval step: create the task, then wait for a decision,
+/// escalating at each escalation point, until the deadline. Decisions are
+/// read back from the task whenever the workflow wakes, so one recorded
+/// while no signal could be delivered is still honoured.
+pub async fn approval_step(
+    activities: &dyn ApprovalActivities,
+    request: CreateApprovalTaskRequest,
+) -> WorkflowServiceResult<ApprovalOutcome> {
+    let policy = request.policy.clone();
+    let mut task = activities.create_approval_task(request).await?;
+    let approval_id = task.approval_id.clone();
+
+    let outcome = loop {
+        if task.status != ApprovalStatus::Pending {
+            break outcome_of(&task, &policy);
+        }
+
+        let now = Utc::now();
+        let next_escalation = policy
+            .escalations
+            .get(task.escalation_level as usize)
+            .map(|escalation| (escalation, task.created_at + chrono::Duration::seconds(escalation.after_seconds as i64)));
+        let wake_at = next_escalation.map_or(task.due_at, |(_, at)| at.min(task.due_at));
+        let wait = (wake_at - now).to_std().unwrap_or(Duration::ZERO);
+
+        if let Some(signal) = activities.wait_for_approval_signal(&approval_id, wait).await {
+            info!("Approval {} {:?} by {}", approval_id, signal.decision, signal.decided_by);
+            task = activities.get_approval_task(&task.tenant_id, &approval_id).await?;
+            continue;
+        }
+
+        task = activities.get_approval_task(&task.tenant_id, &approval_id).await?;
+        if task.status != ApprovalStatus::Pending {
+            continue;
+        }
+
+        let now = Utc::now();
+        if now >= task.due_at {
+            task = activities.expire_approval_task(&task.tenant_id, &approval_id).await?;
+            continue;
+        }
+        if let Some((escalation, at)) = next_escalation {
+            if now >= at {
+                task = activities
+                    .escalate_approval_task(EscalateApprovalTaskRequest {
+                        tenant_id: task.tenant_id.clone(),
+                        approval_id: approval_id.clone(),
+                        level: task.escalation_level + 1,
+                        assignees: escalation.assignees.clone(),
+                    })
+                    .await?;
+            }
+        }
+    };
+
+    activities.close_approval_signals(&approval_id).await;
+    let outcome = outcome?;
+
+    if !outcome.approved && policy.on_reject == RejectAction::Fail {
+        return Err(WorkflowServiceError::ApprovalRejected(match outcome.status {
+            ApprovalStatus::Expired => format!("Approval {} expired without a decision", approval_id),
+            _ => format!(
+                "Approval {} was rejected by {}",
+                approval_id,
+                outcome.decided_by.as_deref().unwrap_or("an assignee")
+            ),
+        }));
+    }
+    Ok(outcome)
+}
+
+fn outcome_of(task: &ApprovalTask, policy: &ApprovalPolicy) -> WorkflowServiceResult<ApprovalOutcome> {
+    let approved = match task.status {
+        ApprovalStatus::Approved => true,
+        ApprovalStatus::Rejected => false,
+        ApprovalStatus::Expired => match policy.on_timeout {
+            TimeoutAction::Approve => true,
+            TimeoutAction::Reject => false,
+            TimeoutAction::Fail => {
+                return Err(WorkflowServiceError::ApprovalTimeout(format!(
+                    "Approval {} was not decided by {}",
+                    task.approval_id, task.due_at
+                )))
+            }
+        },
+        ApprovalStatus::Pending => unreachable!("pending tasks have no outcome"),
+    };
+
+    Ok(ApprovalOutcome {
+        approval_id: task.approval_id.clone(),
+        status: task.status,
+        approved,
+        decided_by: task.decided_by.clone(),
+        comment: task.comment.clone(),
+        decided_at: task.decided_at.unwrap_or(task.updated_at),
+    })
+}
+
+#[derive(Debug, Clone, Copy, Deserialize, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum InboxView {
+    /// Tasks assigned to the caller or one of their roles
+    #[default]
+    Assigned,
+    /// Tasks from workflows the caller started
+    Requested,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ApprovalInboxParams {
+    /// Defaults to pending
+    pub status: Option<ApprovalStatus>,
+    #[serde(default)]
+    pub view: InboxView,
+    pub workflow_id: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct DecideApprovalRequest {
+    pub comment: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ApprovalInboxResponse {
+    pub approvals: Vec<ApprovalTask>,
+    pub total_count: usize,
+}
+
+/// The approvals inbox: lists tasks for a user and records their decisions
+pub struct ApprovalManager {
+    repository: Arc<dyn WorkflowApprovalRepository>,
+    temporal: AdxTemporalClient,
+    signals: Arc<ApprovalSignalRegistry>,
+}
+
+impl ApprovalManager {
+    pub fn new(
+        repository: Arc<dyn WorkflowApprovalRepository>,
+        temporal: AdxTemporalClient,
+        signals: Arc<ApprovalSignalRegistry>,
+    ) -> Self {
+        Self {
+            repository,
+            temporal,
+            signals,
+        }
+    }
+
+    pub async fn inbox(
+        &self,
+        tenant_id: &str,
+        user_id: Option<&str>,
+        roles: &[String],
+        params: ApprovalInboxParams,
+    ) -> WorkflowServiceResult<ApprovalInboxResponse> {
+        let user_id = require_user(user_id)?;
+        let status = params.status.unwrap_or(ApprovalStatus::Pending);
+
+        let approvals = match params.view {
+            InboxView::Assigned => {
+                self.repository
+                    .list_assigned(tenant_id, user_id, roles, status, params.workflow_id.as_deref())
+                    .await?
+            }
+            InboxView::Requested => {
+                self.repository
+                    .list_requested(tenant_id, user_id, status, params.workflow_id.as_deref())
+                    .await?
+            }
+        };
+
+        Ok(ApprovalInboxResponse {
+            total_count: approvals.len(),
+            approvals,
+        })
+    }
+
+    /// Visible to its assignees and to the user who started the workflow
+    pub async fn get_approval(
+        &self,
+        tenant_id: &str,
+        user_id: Option<&str>,
+        roles: &[String],
+        approval_id: &str,
+    ) -> WorkflowServiceResult<ApprovalTask> {
+        let user_id = require_user(user_id)?;
+        let task = self.find(tenant_id, approval_id).await?;
+
+        if !task.assignees.includes(user_id, roles) && task.requested_by.as_deref() != Some(user_id) {
+            return Err(WorkflowServiceError::NotFound(format!("Approval {} not found", approval_id)));
+        }
+        Ok(task)
+    }
+
+    /// Record the caller's decision and signal the waiting workflow. The
+    /// decision stands even if the signal cannot be delivered; the workflow
+    /// reads it back at its next escalation point or deadline.
+    pub async fn decide(
+        &self,
+        tenant_id: &str,
+        user_id: Option<&str>,
+        roles: &[String],
+        approval_id: &str,
+        decision: ApprovalDecision,
+        request: DecideApprovalRequest,
+    ) -> WorkflowServiceResult<ApprovalTask> {
+        let user_id = require_user(user_id)?;
+        let comment = request.comment.map(|c| c.trim().to_string()).filter(|c| !c.is_empty());
+        if comment.as_ref().is_some_and(|c| c.chars().count() > MAX_COMMENT_LENGTH) {
+            return Err(WorkflowServiceError::Validation(format!(
+                "Comments are limited to {} characters",
+                MAX_COMMENT_LENGTH
+            )));
+        }
+
+        let task = self.find(tenant_id, approval_id).await?;
+        if !task.assignees.includes(user_id, roles) {
+            return Err(WorkflowServiceError::Authorization(format!(
+                "Approval {} is not assigned to you",
+                approval_id
+            )));
+        }
+        if !task.allow_self_approval && task.requested_by.as_deref() == Some(user_id) {
+            return Err(WorkflowServiceError::Authorization(
+                "You cannot decide an approval for a workflow you started".to_string(),
+            ));
+        }
+        if task.status != ApprovalStatus::Pending {
+            return Err(WorkflowServiceError::ApprovalClosed(format!(
+                "Approval {} is already {}",
+                approval_id,
+                task.status.as_str()
+            )));
+        }
+
+        // Only the first decision is recorded
+        let task = self
+            .repository
+            .record_decision(tenant_id, approval_id, decision.status(), user_id, comment.as_deref())
+            .await?
+            .ok_or_else(|| WorkflowServiceError::ApprovalClosed(format!("Approval {} was decided already", approval_id)))?;
+
+        let signal = ApprovalSignal {
+            approval_id: task.approval_id.clone(),
+            decision,
+            decided_by: user_id.to_string(),
+            comment,
+        };
+        if let Err(error) = self
+            .temporal
+            .signal_workflow(&task.workflow_id, None, APPROVAL_SIGNAL, signal.clone())
+            .await
+        {
+            warn!("Failed to signal workflow {} with approval {}: {}", task.workflow_id, approval_id, error);
+        }
+        self.signals.send(signal).await;
+
+        info!("Approval {} {} by {}", approval_id, task.status.as_str(), user_id);
+        Ok(task)
+    }
+
+    async fn find(&self, tenant_id: &str, approval_id: &str) -> WorkflowServiceResult<ApprovalTask> {
+        self.repository
+            .get_approval(tenant_id, approval_id)
+            .await?
+            .ok_or_else(|| WorkflowServiceError::NotFound(format!("Approval {} not found", approval_id)))
+    }
+}
+
+pub(crate) fn require_user(user_id: Option<&str>) -> WorkflowServiceResult<&str> {
+    user_id.ok_or_else(|| WorkflowServiceError::Validation("X-User-ID header is required".to_string()))
+}
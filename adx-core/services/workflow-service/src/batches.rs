@@ -0,0 +1,551 @@
+//! Batch workflow launcher.
+//!
+//! A batch runs one built-in workflow once per input, with at most
+//! `parallelism` children in flight. The batch id is the operation id of an
+//! asynchronous workflow response: its status endpoint reports how many
+//! children have settled and, once the batch is done, their results in
+//! input order.
+
+use crate::{
+    activities::{CrossServiceActivities, CrossServiceActivitiesImpl},
+    config::WorkflowServiceConfig,
+    controls::RunControlRegistry,
+    dead_letters::{CaptureDeadLetterRequest, DeadLetterQueue},
+    error::{WorkflowServiceError, WorkflowServiceResult},
+    models::*,
+    monitoring::{CostTracker, RunCostMeter, SlaMonitor},
+    repositories::WorkflowBatchRepository,
+    task_queues::TaskQueueRouter,
+    templates::SUB_WORKFLOW_TYPES,
+    visualization::TENANT_INPUT_FIELDS,
+    workflows::*,
+};
+use adx_shared::temporal::{FanOut, PriorityLane};
+use adx_shared::types::{WorkflowApiResponse, WorkflowProgress, WorkflowStatus, WorkflowStatusResponse};
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Value};
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+pub const MAX_BATCH_SIZE: usize = 1000;
+pub const MAX_BATCH_PARALLELISM: u32 = 20;
+
+const DEFAULT_BATCH_PARALLELISM: u32 = 5;
+const DEFAULT_PAGE_SIZE: u32 = 100;
+const MAX_PAGE_SIZE: u32 = 500;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchStatus {
+    Running,
+    Completed,
+    /// Finished with at least one failed child
+    Failed,
+    Cancelled,
+}
+
+impl BatchStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BatchStatus::Running => "running",
+            BatchStatus::Completed => "completed",
+            BatchStatus::Failed => "failed",
+            BatchStatus::Cancelled => "cancelled",
+        }
+    }
+
+    pub fn parse(value: &str) -> WorkflowServiceResult<Self> {
+        match value {
+            "running" => Ok(BatchStatus::Running),
+            "completed" => Ok(BatchStatus::Completed),
+            "failed" => Ok(BatchStatus::Failed),
+            "cancelled" => Ok(BatchStatus::Cancelled),
+            other => Err(WorkflowServiceError::Internal(format!("Unknown batch status: {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum BatchChildStatus {
+    Pending,
+    Running,
+    Completed,
+    Failed,
+    /// Never started because the batch was cancelled or failed fast
+    Skipped,
+}
+
+impl BatchChildStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BatchChildStatus::Pending => "pending",
+            BatchChildStatus::Running => "running",
+            BatchChildStatus::Completed => "completed",
+            BatchChildStatus::Failed => "failed",
+            BatchChildStatus::Skipped => "skipped",
+        }
+    }
+
+    pub fn parse(value: &str) -> WorkflowServiceResult<Self> {
+        match value {
+            "pending" => Ok(BatchChildStatus::Pending),
+            "running" => Ok(BatchChildStatus::Running),
+            "completed" => Ok(BatchChildStatus::Completed),
+            "failed" => Ok(BatchChildStatus::Failed),
+            "skipped" => Ok(BatchChildStatus::Skipped),
+            other => Err(WorkflowServiceError::Internal(format!("Unknown batch child status: {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+pub struct BatchCounts {
+    pub total: u32,
+    pub pending: u32,
+    pub running: u32,
+    pub completed: u32,
+    pub failed: u32,
+    pub skipped: u32,
+}
+
+impl BatchCounts {
+    /// Children that will not change any more
+    pub fn settled(&self) -> u32 {
+        self.completed + self.failed + self.skipped
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowBatch {
+    pub batch_id: String,
+    pub tenant_id: String,
+    pub workflow_type: String,
+    pub parallelism: u32,
+    pub fail_fast: bool,
+    pub status: BatchStatus,
+    pub cancel_requested: bool,
+    pub counts: BatchCounts,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchChild {
+    pub index: u32,
+    pub workflow_id: String,
+    pub status: BatchChildStatus,
+    pub input: Value,
+    pub output: Option<Value>,
+    pub error: Option<String>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub completed_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SubmitBatchRequest {
+    pub workflow_type: String,
+    pub inputs: Vec<Value>,
+    pub parallelism: Option<u32>,
+    /// Stop starting children once one has failed
+    #[serde(default)]
+    pub fail_fast: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchChildrenParams {
+    pub status: Option<BatchChildStatus>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BatchChildrenResponse {
+    pub children: Vec<BatchChild>,
+    pub total_count: u64,
+}
+
+pub struct WorkflowBatchManager {
+    config: Arc<WorkflowServiceConfig>,
+    repository: Arc<dyn WorkflowBatchRepository>,
+    costs: Arc<CostTracker>,
+    slas: Arc<SlaMonitor>,
+    dead_letters: Arc<DeadLetterQueue>,
+    task_queues: Arc<TaskQueueRouter>,
+    runs: Arc<RunControlRegistry>,
+}
+
+impl WorkflowBatchManager {
+    pub fn new(
+        config: Arc<WorkflowServiceConfig>,
+        repository: Arc<dyn WorkflowBatchRepository>,
+        costs: Arc<CostTracker>,
+        slas: Arc<SlaMonitor>,
+        dead_letters: Arc<DeadLetterQueue>,
+        task_queues: Arc<TaskQueueRouter>,
+        runs: Arc<RunControlRegistry>,
+    ) -> Self {
+        Self {
+            config,
+            repository,
+            costs,
+            slas,
+            dead_letters,
+            task_queues,
+            runs,
+        }
+    }
+
+    /// Validate every input, record the batch and start running it in the
+    /// background. The response points at the batch's status endpoint.
+    pub async fn submit(
+        self: &Arc<Self>,
+        tenant_id: &str,
+        user_id: Option<&str>,
+        request: SubmitBatchRequest,
+    ) -> WorkflowServiceResult<WorkflowApiResponse<Value>> {
+        let user_id = user_id
+            .ok_or_else(|| WorkflowServiceError::Authorization("X-User-ID is required to submit a batch".to_string()))?;
+        let workflow_type = request.workflow_type.as_str();
+        if !SUB_WORKFLOW_TYPES.contains(&workflow_type) {
+            return Err(WorkflowServiceError::Validation(format!(
+                "Workflow type '{}' cannot be run as a batch",
+                workflow_type
+            )));
+        }
+        if request.inputs.is_empty() || request.inputs.len() > MAX_BATCH_SIZE {
+            return Err(WorkflowServiceError::Validation(format!(
+                "A batch takes between 1 and {} inputs",
+                MAX_BATCH_SIZE
+            )));
+        }
+        let parallelism = request.parallelism.unwrap_or(DEFAULT_BATCH_PARALLELISM);
+        if !(1..=MAX_BATCH_PARALLELISM).contains(&parallelism) {
+            return Err(WorkflowServiceError::Validation(format!(
+                "parallelism must be between 1 and {}",
+                MAX_BATCH_PARALLELISM
+            )));
+        }
+
+        for (index, input) in request.inputs.iter().enumerate() {
+            validate_child_input(workflow_type, input)
+                .map_err(|e| WorkflowServiceError::Validation(format!("Input {}: {}", index, e)))?;
+            if !names_only_tenant(input, tenant_id) {
+                return Err(WorkflowServiceError::Authorization(format!(
+                    "Input {} does not run for tenant {}",
+                    index, tenant_id
+                )));
+            }
+        }
+
+        self.costs.check_starts(tenant_id, workflow_type, request.inputs.len()).await?;
+
+        let now = Utc::now();
+        let batch = WorkflowBatch {
+            batch_id: Uuid::new_v4().to_string(),
+            tenant_id: tenant_id.to_string(),
+            workflow_type: workflow_type.to_string(),
+            parallelism,
+            fail_fast: request.fail_fast,
+            status: BatchStatus::Running,
+            cancel_requested: false,
+            counts: BatchCounts {
+                total: request.inputs.len() as u32,
+                pending: request.inputs.len() as u32,
+                ..BatchCounts::default()
+            },
+            created_by: user_id.to_string(),
+            created_at: now,
+            updated_at: now,
+            completed_at: None,
+        };
+        let children: Vec<(String, Value)> = request
+            .inputs
+            .iter()
+            .enumerate()
+            .map(|(index, input)| (child_workflow_id(&batch, index), input.clone()))
+            .collect();
+        self.repository.create_batch(&batch, &children).await?;
+
+        info!(
+            "Submitted batch {} of {} {} runs for tenant {} (parallelism {})",
+            batch.batch_id, batch.counts.total, batch.workflow_type, tenant_id, parallelism
+        );
+
+        let response = WorkflowApiResponse::Asynchronous {
+            operation_id: batch.batch_id.clone(),
+            status_url: format!("/api/v1/workflow-batches/{}", batch.batch_id),
+            stream_url: None,
+            estimated_duration_seconds: None,
+        };
+
+        let manager = Arc::clone(self);
+        tokio::spawn(async move { manager.run_batch(batch, request.inputs).await });
+
+        Ok(response)
+    }
+
+    pub async fn get_batch(&self, tenant_id: &str, batch_id: &str) -> WorkflowServiceResult<WorkflowBatch> {
+        self.repository
+            .get_batch(tenant_id, batch_id)
+            .await?
+            .ok_or_else(|| WorkflowServiceError::NotFound(format!("Batch {} not found", batch_id)))
+    }
+
+    /// The batch as a workflow operation: progress while it runs, and the
+    /// children's outputs in input order once it has finished
+    pub async fn status(&self, tenant_id: &str, batch_id: &str) -> WorkflowServiceResult<WorkflowStatusResponse> {
+        let batch = self.get_batch(tenant_id, batch_id).await?;
+        let counts = &batch.counts;
+        let settled = counts.settled();
+
+        let status = match batch.status {
+            BatchStatus::Running if settled == 0 && counts.running == 0 => WorkflowStatus::Pending,
+            BatchStatus::Running => WorkflowStatus::Running,
+            BatchStatus::Completed => WorkflowStatus::Completed,
+            BatchStatus::Failed => WorkflowStatus::Failed,
+            BatchStatus::Cancelled => WorkflowStatus::Cancelled,
+        };
+
+        let result = if batch.status == BatchStatus::Running {
+            None
+        } else {
+            let (children, _) = self
+                .repository
+                .list_children(tenant_id, batch_id, None, counts.total, 0)
+                .await?;
+            Some(json!({
+                "completed": counts.completed,
+                "failed": counts.failed,
+                "skipped": counts.skipped,
+                "outputs": children.iter().map(|child| child.output.clone().unwrap_or(Value::Null)).collect::<Vec<_>>(),
+            }))
+        };
+
+        // Extrapolate from the children settled so far
+        let estimated_completion = (batch.status == BatchStatus::Running && settled > 0).then(|| {
+            let elapsed = Utc::now() - batch.created_at;
+            batch.created_at + elapsed * counts.total as i32 / settled as i32
+        });
+
+        Ok(WorkflowStatusResponse {
+            operation_id: batch.batch_id.clone(),
+            progress: Some(WorkflowProgress {
+                current_step: format!("{} running, {} pending", counts.running, counts.pending),
+                total_steps: counts.total,
+                completed_steps: settled,
+                percentage: settled as f32 / counts.total.max(1) as f32 * 100.0,
+                message: Some(format!(
+                    "{} completed, {} failed, {} skipped",
+                    counts.completed, counts.failed, counts.skipped
+                )),
+            }),
+            error: (counts.failed > 0).then(|| format!("{} of {} children failed", counts.failed, counts.total)),
+            status,
+            result,
+            started_at: batch.created_at,
+            updated_at: batch.updated_at,
+            estimated_completion,
+        })
+    }
+
+    pub async fn list_children(
+        &self,
+        tenant_id: &str,
+        batch_id: &str,
+        params: BatchChildrenParams,
+    ) -> WorkflowServiceResult<BatchChildrenResponse> {
+        self.get_batch(tenant_id, batch_id).await?;
+        let limit = params.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+        let (children, total_count) = self
+            .repository
+            .list_children(tenant_id, batch_id, params.status, limit, params.offset.unwrap_or(0))
+            .await?;
+
+        Ok(BatchChildrenResponse { children, total_count })
+    }
+
+    /// Stop starting children. Children already running finish, and the
+    /// rest are skipped.
+    pub async fn cancel(&self, tenant_id: &str, user_id: Option<&str>, batch_id: &str) -> WorkflowServiceResult<WorkflowBatch> {
+        let user_id = user_id
+            .ok_or_else(|| WorkflowServiceError::Authorization("X-User-ID is required to cancel a batch".to_string()))?;
+        self.get_batch(tenant_id, batch_id).await?;
+        if !self.repository.request_cancel(tenant_id, batch_id).await? {
+            return Err(WorkflowServiceError::Validation(format!(
+                "Batch {} has already finished",
+                batch_id
+            )));
+        }
+        info!("Batch {} cancelled by user {}", batch_id, user_id);
+
+        self.get_batch(tenant_id, batch_id).await
+    }
+
+    async fn run_batch(&self, batch: WorkflowBatch, inputs: Vec<Value>) {
+        FanOut::new(batch.parallelism as usize)
+            .fail_fast(batch.fail_fast)
+            .run(inputs, |index, input| self.run_child(&batch, index, input))
+            .await;
+
+        let finished = async {
+            self.repository.skip_pending_children(&batch.tenant_id, &batch.batch_id).await?;
+            let current = self.get_batch(&batch.tenant_id, &batch.batch_id).await?;
+            let status = if current.cancel_requested {
+                BatchStatus::Cancelled
+            } else if current.counts.failed > 0 {
+                BatchStatus::Failed
+            } else {
+                BatchStatus::Completed
+            };
+            self.repository.finish_batch(&batch.tenant_id, &batch.batch_id, status).await?;
+            WorkflowServiceResult::Ok((status, current.counts))
+        };
+
+        match finished.await {
+            Ok((status, counts)) => info!(
+                "Batch {} for tenant {} finished as {}: {} completed, {} failed, {} skipped",
+                batch.batch_id,
+                batch.tenant_id,
+                status.as_str(),
+                counts.completed,
+                counts.failed,
+                counts.skipped
+            ),
+            Err(error) => warn!("Failed to finish batch {}: {}", batch.batch_id, error),
+        }
+
+        // Children are checked against the SLA together rather than one by one
+        if let Err(error) = self.slas.run_finished(&batch.tenant_id, &batch.workflow_type).await {
+            warn!("Failed to check SLA of {} after batch {}: {}", batch.workflow_type, batch.batch_id, error);
+        }
+    }
+
+    /// Run one child inline. Returns `None` when the child was not started
+    /// because the batch has been cancelled.
+    async fn run_child(&self, batch: &WorkflowBatch, index: usize, input: Value) -> Result<Option<Value>, String> {
+        let started = self
+            .repository
+            .start_child(&batch.tenant_id, &batch.batch_id, index as u32)
+            .await
+            .map_err(|e| e.to_string())?;
+        if !started {
+            return Ok(None);
+        }
+
+        let workflow_id = child_workflow_id(batch, index);
+        let meter = Arc::new(RunCostMeter::new());
+        let control = self
+            .runs
+            .register(&batch.tenant_id, &workflow_id, &batch.workflow_type, Some(&batch.created_by))
+            .in_lane(PriorityLane::Batch);
+        let activities = CrossServiceActivitiesImpl::new((*self.config).clone())
+            .with_meter(meter.clone())
+            .with_control(control);
+        let result = run_child_workflow(&batch.workflow_type, input.clone(), &activities).await;
+
+        if let Err(error) = self
+            .costs
+            .record_run(&batch.tenant_id, &workflow_id, &batch.workflow_type, &meter, result.is_ok())
+            .await
+        {
+            warn!("Failed to record cost of workflow {}: {}", workflow_id, error);
+        }
+
+        let (status, output, error) = match &result {
+            Ok(output) => (BatchChildStatus::Completed, Some(output), None),
+            Err(error) => (BatchChildStatus::Failed, None, Some(error.to_string())),
+        };
+        if let Err(update_error) = self
+            .repository
+            .finish_child(&batch.tenant_id, &batch.batch_id, index as u32, status, output, error.as_deref())
+            .await
+        {
+            warn!("Failed to record result of batch child {}: {}", workflow_id, update_error);
+        }
+
+        match (result, error) {
+            (Ok(output), _) => Ok(Some(output)),
+            (Err(WorkflowServiceError::WorkflowCancelled(_)), error) => Err(error.unwrap_or_default()),
+            (Err(_), error) => {
+                let error = error.unwrap_or_default();
+                self.dead_letter(batch, &workflow_id, input, &error).await;
+                Err(error)
+            }
+        }
+    }
+
+    async fn dead_letter(&self, batch: &WorkflowBatch, workflow_id: &str, input: Value, error: &str) {
+        let task_queue = match self.task_queues.route(&batch.tenant_id, &batch.workflow_type).await {
+            Ok(task_queue) => task_queue,
+            Err(route_error) => {
+                warn!("Failed to route workflow {} for dead-lettering: {}", workflow_id, route_error);
+                self.task_queues.default_queue(&batch.workflow_type)
+            }
+        };
+        let request = CaptureDeadLetterRequest {
+            tenant_id: batch.tenant_id.clone(),
+            workflow_id: workflow_id.to_string(),
+            run_id: None,
+            workflow_type: batch.workflow_type.clone(),
+            task_queue,
+            input,
+            failure_message: error.to_string(),
+            failure_details: json!({ "error": error, "batch_id": batch.batch_id }),
+            started_by: Some(batch.created_by.clone()),
+        };
+        if let Err(capture_error) = self.dead_letters.capture(request).await {
+            warn!("Failed to dead-letter workflow {}: {}", workflow_id, capture_error);
+        }
+    }
+}
+
+fn child_workflow_id(batch: &WorkflowBatch, index: usize) -> String {
+    format!(
+        "{}_{}-{}",
+        batch.workflow_type.trim_end_matches("_workflow"),
+        batch.batch_id,
+        index
+    )
+}
+
+/// Every tenant an input names must be the caller's
+pub(crate) fn names_only_tenant(input: &Value, tenant_id: &str) -> bool {
+    let mut named = TENANT_INPUT_FIELDS.iter().filter_map(|field| input.get(field)).peekable();
+    named.peek().is_some() && named.all(|value| value.as_str() == Some(tenant_id))
+}
+
+fn child_request<T: DeserializeOwned>(input: Value) -> WorkflowServiceResult<T> {
+    serde_json::from_value(input).map_err(|e| WorkflowServiceError::Validation(e.to_string()))
+}
+
+pub(crate) fn validate_child_input(workflow_type: &str, input: &Value) -> WorkflowServiceResult<()> {
+    match workflow_type {
+        "user_onboarding_workflow" => child_request::<UserOnboardingRequest>(input.clone()).map(|_| ()),
+        "tenant_switching_workflow" => child_request::<TenantSwitchingRequest>(input.clone()).map(|_| ()),
+        "data_migration_workflow" => child_request::<DataMigrationRequest>(input.clone()).map(|_| ()),
+        "bulk_operation_workflow" => child_request::<BulkOperationRequest>(input.clone()).map(|_| ()),
+        "compliance_workflow" => child_request::<ComplianceWorkflowRequest>(input.clone()).map(|_| ()),
+        other => Err(WorkflowServiceError::Validation(format!("Unknown workflow '{}'", other))),
+    }
+}
+
+pub(crate) async fn run_child_workflow(
+    workflow_type: &str,
+    input: Value,
+    activities: &dyn CrossServiceActivities,
+) -> WorkflowServiceResult<Value> {
+    let output = match workflow_type {
+        "user_onboarding_workflow" => serde_json::to_value(user_onboarding_workflow(child_request(input)?, activities).await?)?,
+        "tenant_switching_workflow" => serde_json::to_value(tenant_switching_workflow(child_request(input)?, activities).await?)?,
+        "data_migration_workflow" => serde_json::to_value(data_migration_workflow(child_request(input)?, activities).await?)?,
+        "bulk_operation_workflow" => serde_json::to_value(bulk_operation_workflow(child_request(input)?, activities).await?)?,
+        "compliance_workflow" => serde_json::to_value(compliance_workflow(child_request(input)?, activities).await?)?,
+        other => return Err(WorkflowServiceError::Validation(format!("Unknown workflow '{}'", other))),
+    };
+    Ok(output)
+}
@@ -1,4 +1,6 @@
+use adx_shared::temporal::PriorityLane;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::time::Duration;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -7,6 +9,10 @@ pub struct WorkflowServiceConfig {
     pub temporal: TemporalConfig,
     pub services: ServiceEndpoints,
     pub workflows: WorkflowConfig,
+    pub schedules: ScheduleConfig,
+    pub task_queues: TaskQueueConfig,
+    pub costs: CostConfig,
+    pub triggers: TriggerConfig,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -32,6 +38,8 @@ pub struct ServiceEndpoints {
     pub user_service: String,
     pub tenant_service: String,
     pub file_service: String,
+    pub license_service: String,
+    pub module_service: String,
     pub api_gateway: String,
 }
 
@@ -42,6 +50,64 @@ pub struct WorkflowConfig {
     pub batch_size: usize,
 }
 
+/// Service-wide schedule limits; per-tenant overrides are stored in the database
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleConfig {
+    pub max_schedules_per_tenant: u32,
+    pub min_interval_seconds: u32,
+    pub max_jitter_seconds: u32,
+}
+
+/// How workflow runs are spread over Temporal task queues. Batch workflow
+/// types get their own queue next to every interactive one, and tenants can
+/// be assigned a tier's queues or dedicated queues; assignments are stored
+/// in the database.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskQueueConfig {
+    /// Workflow types routed to batch queues
+    pub batch_workflow_types: Vec<String>,
+    /// Appended to a queue name to get its batch queue
+    pub batch_queue_suffix: String,
+    /// Interactive queue for each subscription tier that has its own
+    pub tier_queues: HashMap<String, String>,
+    /// Queues that may be dedicated to a single tenant
+    pub dedicated_queues: Vec<String>,
+    /// Share of a worker's task slots each batch queue may use
+    pub batch_slot_percent: u32,
+    /// Longest a batch run waits at a checkpoint for interactive runs to
+    /// finish before carrying on
+    pub batch_max_yield_seconds: u64,
+}
+
+/// Rates used to price metered workflow runs. Monthly budgets are set per
+/// tenant in the database; tenants without one are not limited.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostConfig {
+    /// Price of one second of activity execution time
+    pub activity_second_cost: f64,
+    /// Price of one call to a downstream service, by service name
+    pub service_call_costs: HashMap<String, f64>,
+    /// Price of a call to a service without its own rate
+    pub default_call_cost: f64,
+    /// Workflow types that may start even when the tenant is over budget
+    pub critical_workflow_types: Vec<String>,
+}
+
+/// Event triggers and the dispatcher that evaluates received events
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerConfig {
+    pub max_triggers_per_tenant: u32,
+    /// How often the dispatcher looks for queued events
+    pub dispatch_interval_seconds: u64,
+    /// Events claimed per pass
+    pub dispatch_batch_size: u32,
+    /// Attempts an event gets before it is marked failed
+    pub max_dispatch_attempts: u32,
+    /// Wait before a failed event is tried again, multiplied by its attempts
+    /// so far; also how long a claimed event is hidden from other dispatchers
+    pub retry_delay_seconds: u64,
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RetryPolicyConfig {
     pub initial_interval: Duration,
@@ -50,6 +116,118 @@ pub struct RetryPolicyConfig {
     pub maximum_attempts: u32,
 }
 
+impl TemporalConfig {
+    /// Settings for the shared Temporal client, which takes a bare host:port
+    pub fn client_config(&self) -> adx_shared::temporal::TemporalConfig {
+        let server_address = self
+            .server_url
+            .trim_start_matches("http://")
+            .trim_start_matches("https://")
+            .to_string();
+
+        adx_shared::temporal::TemporalConfig {
+            server_address,
+            namespace: self.namespace.clone(),
+            client_identity: self.worker_identity.clone(),
+            ..Default::default()
+        }
+    }
+}
+
+impl TaskQueueConfig {
+    pub fn is_batch(&self, workflow_type: &str) -> bool {
+        self.batch_workflow_types.iter().any(|batch_type| batch_type == workflow_type)
+    }
+
+    pub fn batch_queue(&self, task_queue: &str) -> String {
+        format!("{}{}", task_queue, self.batch_queue_suffix)
+    }
+
+    /// Interactive queues runs could be routed to: the default, tier and
+    /// dedicated queues
+    pub fn batch_max_yield(&self) -> Duration {
+        Duration::from_secs(self.batch_max_yield_seconds)
+    }
+
+    pub fn interactive_queues(&self, default_queue: &str) -> Vec<String> {
+        let mut queues = vec![default_queue.to_string()];
+        queues.extend(self.tier_queues.values().cloned());
+        queues.extend(self.dedicated_queues.iter().cloned());
+        queues.sort();
+        queues.dedup();
+        queues
+    }
+}
+
+impl ServiceEndpoints {
+    /// Short name of the service at a base URL, as used for health checks
+    /// and call rates
+    pub fn service_name(&self, service_url: &str) -> &'static str {
+        match service_url {
+            url if url == self.auth_service => "auth",
+            url if url == self.user_service => "user",
+            url if url == self.tenant_service => "tenant",
+            url if url == self.file_service => "file",
+            url if url == self.license_service => "license",
+            url if url == self.module_service => "module",
+            url if url == self.api_gateway => "api_gateway",
+            _ => "other",
+        }
+    }
+}
+
+impl CostConfig {
+    pub fn call_cost(&self, service: &str) -> f64 {
+        self.service_call_costs.get(service).copied().unwrap_or(self.default_call_cost)
+    }
+
+    pub fn is_critical(&self, workflow_type: &str) -> bool {
+        self.critical_workflow_types.iter().any(|critical| critical == workflow_type)
+    }
+}
+
+impl TriggerConfig {
+    pub fn dispatch_interval(&self) -> Duration {
+        Duration::from_secs(self.dispatch_interval_seconds.max(1))
+    }
+}
+
+impl WorkflowServiceConfig {
+    /// Worker settings polling every routable queue, with batch queues in
+    /// their own pool held to their share of the task slots
+    pub fn worker_config(&self) -> adx_shared::temporal::WorkerConfig {
+        let max_workflow_tasks = self.temporal.max_concurrent_workflows;
+        let max_activity_tasks = self.temporal.max_concurrent_activities;
+        let share = |max: usize| (max * self.task_queues.batch_slot_percent as usize / 100).max(1);
+
+        let batch_limits = adx_shared::temporal::TaskQueueLimits {
+            max_concurrent_workflow_tasks: share(max_workflow_tasks),
+            max_concurrent_activity_tasks: share(max_activity_tasks),
+        };
+
+        let mut task_queues = Vec::new();
+        let mut task_queue_limits = HashMap::new();
+        let mut task_queue_lanes = HashMap::new();
+        for queue in self.task_queues.interactive_queues(&self.temporal.task_queue) {
+            let batch_queue = self.task_queues.batch_queue(&queue);
+            task_queue_limits.insert(batch_queue.clone(), batch_limits);
+            task_queue_lanes.insert(batch_queue.clone(), PriorityLane::Batch);
+            task_queues.push(queue);
+            task_queues.push(batch_queue);
+        }
+
+        adx_shared::temporal::WorkerConfig {
+            max_concurrent_workflow_tasks: max_workflow_tasks,
+            max_concurrent_activity_tasks: max_activity_tasks,
+            identity: self.temporal.worker_identity.clone(),
+            task_queues,
+            task_queue_limits,
+            task_queue_lanes,
+            ..Default::default()
+        }
+    }
+}
+
 impl Default for WorkflowServiceConfig {
     fn default() -> Self {
         Self {
@@ -71,6 +249,8 @@ impl Default for WorkflowServiceConfig {
                 user_service: "http://localhost:8082".to_string(),
                 tenant_service: "http://localhost:8085".to_string(),
                 file_service: "http://localhost:8083".to_string(),
+                license_service: "http://localhost:8087".to_string(),
+                module_service: "http://localhost:8086".to_string(),
                 api_gateway: "http://localhost:8080".to_string(),
             },
             workflows: WorkflowConfig {
@@ -83,6 +263,46 @@ impl Default for WorkflowServiceConfig {
                 },
                 batch_size: 100,
             },
+            schedules: ScheduleConfig {
+                max_schedules_per_tenant: 50,
+                min_interval_seconds: 300, // 5 minutes
+                max_jitter_seconds: 3600,
+            },
+            task_queues: TaskQueueConfig {
+                batch_workflow_types: vec![
+                    "bulk_operation_workflow".to_string(),
+                    "data_migration_workflow".to_string(),
+                ],
+                batch_queue_suffix: "-batch".to_string(),
+                tier_queues: HashMap::from([(
+                    "enterprise".to_string(),
+                    "workflow-service-enterprise-queue".to_string(),
+                )]),
+                dedicated_queues: Vec::new(),
+                batch_slot_percent: 25,
+                batch_max_yield_seconds: 30,
+            },
+            costs: CostConfig {
+                activity_second_cost: 0.0001,
+                service_call_costs: HashMap::from([
+                    ("auth".to_string(), 0.0005),
+                    ("user".to_string(), 0.0005),
+                    ("tenant".to_string(), 0.0005),
+                    ("file".to_string(), 0.002),
+                ]),
+                default_call_cost: 0.001,
+                critical_workflow_types: vec![
+                    "compliance_workflow".to_string(),
+                    "tenant_switching_workflow".to_string(),
+                ],
+            },
+            triggers: TriggerConfig {
+                max_triggers_per_tenant: 100,
+                dispatch_interval_seconds: 5,
+                dispatch_batch_size: 50,
+                max_dispatch_attempts: 5,
+                retry_delay_seconds: 30,
+            },
         }
     }
 }
\ No newline at end of file
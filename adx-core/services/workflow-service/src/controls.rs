@@ -0,0 +1,427 @@
+//! Pause, resume and cancel controls for running workflows.
+//!
+//! Runs executed inside the service register a `RunControl` for as long as
+//! they run and call `checkpoint` between units of work: a paused run waits
+//! there and a cancelled one fails with `WorkflowCancelled`. Temporal runs
+//! are sent the `pause` and `resume` signals and a cancellation request.
+//! Only the user who started a run or an operator may control it, and every
+//! request is kept in the run's control history.
+//!
+//! Checkpoints are also where batch-priority runs step aside: while
+//! interactive runs are in flight, a batch run waits at its next checkpoint
+//! for them to finish, up to `batch_max_yield_seconds`.
+
+use crate::{
+    approvals::require_user,
+    error::{WorkflowServiceError, WorkflowServiceResult},
+    interactions::{Caller, OPERATOR_ROLE},
+    repositories::WorkflowControlRepository,
+    visualization::{run_is_closed, started_workflow_type, tenant_history},
+};
+use adx_shared::temporal::{AdxTemporalClient, InteractiveGuard, PriorityLane, PriorityLanes};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::{
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+use tokio::sync::watch;
+use tracing::info;
+use uuid::Uuid;
+
+pub const PAUSE_SIGNAL: &str = "pause";
+pub const RESUME_SIGNAL: &str = "resume";
+
+const MAX_REASON_LENGTH: usize = 2000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RunState {
+    Running,
+    Paused,
+    Cancelled,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ControlAction {
+    Pause,
+    Resume,
+    Cancel,
+}
+
+impl ControlAction {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ControlAction::Pause => "pause",
+            ControlAction::Resume => "resume",
+            ControlAction::Cancel => "cancel",
+        }
+    }
+
+    pub fn parse(value: &str) -> WorkflowServiceResult<Self> {
+        match value {
+            "pause" => Ok(ControlAction::Pause),
+            "resume" => Ok(ControlAction::Resume),
+            "cancel" => Ok(ControlAction::Cancel),
+            other => Err(WorkflowServiceError::Internal(format!("Unknown control action: {}", other))),
+        }
+    }
+
+    /// State the run is left in, or `None` when the action doesn't apply
+    /// to a run in `state`
+    fn apply(&self, state: RunState) -> Option<RunState> {
+        match (self, state) {
+            (ControlAction::Pause, RunState::Running) => Some(RunState::Paused),
+            (ControlAction::Resume, RunState::Paused) => Some(RunState::Running),
+            (ControlAction::Cancel, RunState::Running | RunState::Paused) => Some(RunState::Cancelled),
+            _ => None,
+        }
+    }
+}
+
+struct ControlledRun {
+    tenant_id: String,
+    workflow_type: String,
+    started_by: Option<String>,
+    state: watch::Sender<RunState>,
+}
+
+/// Runs executing inside the service, keyed by workflow id. Stands in for
+/// Temporal signals and cancellation scopes for runs that don't go through
+/// Temporal.
+#[derive(Default)]
+pub struct RunControlRegistry {
+    runs: Mutex<HashMap<String, ControlledRun>>,
+    lanes: PriorityLanes,
+}
+
+impl RunControlRegistry {
+    pub fn new(lanes: PriorityLanes) -> Self {
+        Self {
+            runs: Mutex::default(),
+            lanes,
+        }
+    }
+
+    /// Make a run controllable until the returned handle is dropped
+    pub fn register(
+        self: &Arc<Self>,
+        tenant_id: &str,
+        workflow_id: &str,
+        workflow_type: &str,
+        started_by: Option<&str>,
+    ) -> RunControl {
+        let (sender, receiver) = watch::channel(RunState::Running);
+        self.runs.lock().unwrap().insert(
+            workflow_id.to_string(),
+            ControlledRun {
+                tenant_id: tenant_id.to_string(),
+                workflow_type: workflow_type.to_string(),
+                started_by: started_by.map(str::to_string),
+                state: sender,
+            },
+        );
+
+        RunControl {
+            registry: self.clone(),
+            workflow_id: workflow_id.to_string(),
+            state: receiver,
+            _interactive: None,
+            batch: false,
+        }
+    }
+
+    /// Type, starter and state of a run of the tenant
+    fn lookup(&self, tenant_id: &str, workflow_id: &str) -> Option<(String, Option<String>, RunState)> {
+        let runs = self.runs.lock().unwrap();
+        let run = runs.get(workflow_id).filter(|run| run.tenant_id == tenant_id)?;
+        let state = *run.state.borrow();
+        Some((run.workflow_type.clone(), run.started_by.clone(), state))
+    }
+
+    /// Move a run from `from` to `to`; false when it has finished or was
+    /// moved by someone else in the meantime
+    fn transition(&self, workflow_id: &str, from: RunState, to: RunState) -> bool {
+        match self.runs.lock().unwrap().get(workflow_id) {
+            Some(run) => run.state.send_if_modified(|state| {
+                if *state != from {
+                    return false;
+                }
+                *state = to;
+                true
+            }),
+            None => false,
+        }
+    }
+
+    fn release(&self, workflow_id: &str) {
+        self.runs.lock().unwrap().remove(workflow_id);
+    }
+}
+
+/// A run's view of its controls
+pub struct RunControl {
+    registry: Arc<RunControlRegistry>,
+    workflow_id: String,
+    state: watch::Receiver<RunState>,
+    /// Held by an interactive run for as long as it runs
+    _interactive: Option<InteractiveGuard>,
+    batch: bool,
+}
+
+impl RunControl {
+    /// Schedule the run in a priority lane. Interactive runs hold batch runs
+    /// back until they finish.
+    pub fn in_lane(mut self, lane: PriorityLane) -> Self {
+        match lane {
+            PriorityLane::Interactive => self._interactive = Some(self.registry.lanes.admit_interactive()),
+            PriorityLane::Batch => self.batch = true,
+        }
+        self
+    }
+
+    /// Wait while the run is paused, or while a batch run has to make way
+    /// for interactive ones; fails once the run has been cancelled
+    pub async fn checkpoint(&self) -> WorkflowServiceResult<()> {
+        if self.batch {
+            self.registry.lanes.yield_to_interactive().await;
+        }
+
+        let mut state = self.state.clone();
+        let settled = match state.wait_for(|state| *state != RunState::Paused).await {
+            Ok(state) => *state,
+            Err(_) => RunState::Running,
+        };
+
+        if settled == RunState::Cancelled {
+            return Err(WorkflowServiceError::WorkflowCancelled(format!(
+                "Workflow {} was cancelled",
+                self.workflow_id
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl Drop for RunControl {
+    fn drop(&mut self) {
+        self.registry.release(&self.workflow_id);
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct WorkflowControlRequest {
+    pub run_id: Option<String>,
+    /// Required to pause or cancel
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowControlRecord {
+    pub action_id: String,
+    pub tenant_id: String,
+    pub workflow_id: String,
+    pub run_id: Option<String>,
+    pub workflow_type: String,
+    pub action: ControlAction,
+    pub reason: Option<String>,
+    pub requested_by: String,
+    pub inline_run: bool,
+    pub requested_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkflowControlResponse {
+    pub workflow_id: String,
+    pub run_id: Option<String>,
+    pub workflow_type: String,
+    pub action: ControlAction,
+    pub state: RunState,
+    pub requested_by: String,
+    pub requested_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkflowControlHistory {
+    pub workflow_id: String,
+    /// Newest first
+    pub actions: Vec<WorkflowControlRecord>,
+}
+
+/// The run a control request targets
+struct Target {
+    workflow_type: String,
+    started_by: Option<String>,
+    state: RunState,
+    inline_run: bool,
+}
+
+pub struct WorkflowControlManager {
+    repository: Arc<dyn WorkflowControlRepository>,
+    temporal: AdxTemporalClient,
+    runs: Arc<RunControlRegistry>,
+}
+
+impl WorkflowControlManager {
+    pub fn new(
+        repository: Arc<dyn WorkflowControlRepository>,
+        temporal: AdxTemporalClient,
+        runs: Arc<RunControlRegistry>,
+    ) -> Self {
+        Self {
+            repository,
+            temporal,
+            runs,
+        }
+    }
+
+    pub async fn control(
+        &self,
+        caller: &Caller<'_>,
+        workflow_id: &str,
+        action: ControlAction,
+        request: WorkflowControlRequest,
+    ) -> WorkflowServiceResult<WorkflowControlResponse> {
+        let user_id = require_user(caller.user_id)?;
+        // Checked up front so the audit entry can't fail once the run has been changed
+        if Uuid::parse_str(user_id).is_err() {
+            return Err(WorkflowServiceError::Validation(format!("Invalid user id: {}", user_id)));
+        }
+        let reason = request.reason.map(|reason| reason.trim().to_string()).filter(|reason| !reason.is_empty());
+        if reason.is_none() && action != ControlAction::Resume {
+            return Err(WorkflowServiceError::Validation(format!(
+                "A reason is required to {} a workflow",
+                action.as_str()
+            )));
+        }
+        if reason.as_ref().is_some_and(|reason| reason.len() > MAX_REASON_LENGTH) {
+            return Err(WorkflowServiceError::Validation(format!(
+                "reason must be at most {} characters",
+                MAX_REASON_LENGTH
+            )));
+        }
+
+        let target = self.target(caller, workflow_id, request.run_id.as_deref()).await?;
+        let is_operator = caller.roles.iter().any(|role| role == OPERATOR_ROLE);
+        if !is_operator && target.started_by.as_deref() != Some(user_id) {
+            return Err(WorkflowServiceError::Authorization(format!(
+                "Only the user who started workflow {} or an operator can {} it",
+                workflow_id,
+                action.as_str()
+            )));
+        }
+        let state = action.apply(target.state).ok_or_else(|| {
+            WorkflowServiceError::InvalidOperation(format!(
+                "Workflow {} is {:?} and can't be asked to {}",
+                workflow_id,
+                target.state,
+                action.as_str()
+            ))
+        })?;
+
+        if target.inline_run {
+            if !self.runs.transition(workflow_id, target.state, state) {
+                return Err(WorkflowServiceError::InvalidOperation(format!(
+                    "Workflow {} changed state while the request was handled; try again",
+                    workflow_id
+                )));
+            }
+        } else {
+            self.signal_temporal(workflow_id, request.run_id.as_deref(), action, reason.as_deref(), user_id)
+                .await?;
+        }
+
+        let record = WorkflowControlRecord {
+            action_id: Uuid::new_v4().to_string(),
+            tenant_id: caller.tenant_id.to_string(),
+            workflow_id: workflow_id.to_string(),
+            run_id: request.run_id,
+            workflow_type: target.workflow_type,
+            action,
+            reason,
+            requested_by: user_id.to_string(),
+            inline_run: target.inline_run,
+            requested_at: Utc::now(),
+        };
+        self.repository.record_action(&record).await?;
+
+        info!(
+            workflow_id = workflow_id,
+            action = action.as_str(),
+            user_id = user_id,
+            tenant_id = caller.tenant_id,
+            "Workflow control requested"
+        );
+
+        Ok(WorkflowControlResponse {
+            workflow_id: record.workflow_id,
+            run_id: record.run_id,
+            workflow_type: record.workflow_type,
+            action,
+            state,
+            requested_by: record.requested_by,
+            requested_at: record.requested_at,
+        })
+    }
+
+    pub async fn history(&self, tenant_id: &str, workflow_id: &str) -> WorkflowServiceResult<WorkflowControlHistory> {
+        Ok(WorkflowControlHistory {
+            workflow_id: workflow_id.to_string(),
+            actions: self.repository.list_actions(tenant_id, workflow_id).await?,
+        })
+    }
+
+    /// Find the run among the inline runs first, then in Temporal. Runs of
+    /// other tenants are reported as not found.
+    async fn target(&self, caller: &Caller<'_>, workflow_id: &str, run_id: Option<&str>) -> WorkflowServiceResult<Target> {
+        if let Some((workflow_type, started_by, state)) = self.runs.lookup(caller.tenant_id, workflow_id) {
+            return Ok(Target {
+                workflow_type,
+                started_by,
+                state,
+                inline_run: true,
+            });
+        }
+
+        let events = tenant_history(&self.temporal, caller.tenant_id, workflow_id, run_id).await?;
+        let workflow_type = started_workflow_type(&events).ok_or_else(|| {
+            WorkflowServiceError::Temporal(format!("History of workflow {} has no workflow type", workflow_id))
+        })?;
+        if run_is_closed(&events) {
+            return Err(WorkflowServiceError::InvalidOperation(format!(
+                "Workflow {} is not running",
+                workflow_id
+            )));
+        }
+
+        let paused = self.repository.last_pause_action(caller.tenant_id, workflow_id).await? == Some(ControlAction::Pause);
+        Ok(Target {
+            workflow_type,
+            started_by: self.repository.execution_owner(caller.tenant_id, workflow_id).await?,
+            state: if paused { RunState::Paused } else { RunState::Running },
+            inline_run: false,
+        })
+    }
+
+    async fn signal_temporal(
+        &self,
+        workflow_id: &str,
+        run_id: Option<&str>,
+        action: ControlAction,
+        reason: Option<&str>,
+        user_id: &str,
+    ) -> WorkflowServiceResult<()> {
+        let payload = serde_json::json!({ "reason": reason, "requested_by": user_id });
+        let sent = match action {
+            ControlAction::Pause => self.temporal.signal_workflow(workflow_id, run_id, PAUSE_SIGNAL, payload).await,
+            ControlAction::Resume => self.temporal.signal_workflow(workflow_id, run_id, RESUME_SIGNAL, payload).await,
+            ControlAction::Cancel => {
+                self.temporal
+                    .cancel_workflow(workflow_id, run_id, reason.unwrap_or_default())
+                    .await
+            }
+        };
+        sent.map_err(|e| WorkflowServiceError::Temporal(e.to_string()))
+    }
+}
@@ -0,0 +1,228 @@
+use chrono::{DateTime, Datelike, Duration, NaiveDate, TimeZone, Timelike, Utc};
+use chrono_tz::Tz;
+use std::fmt;
+
+/// How far ahead to look for the next run before giving up. Expressions such
+/// as `0 0 30 2 *` parse but never fire.
+const MAX_LOOKAHEAD_DAYS: i64 = 366 * 5;
+
+const MONTH_NAMES: [&str; 12] = ["JAN", "FEB", "MAR", "APR", "MAY", "JUN", "JUL", "AUG", "SEP", "OCT", "NOV", "DEC"];
+const DAY_NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+
+/// A standard five-field cron expression (minute, hour, day of month, month,
+/// day of week), the same dialect Temporal accepts in a schedule spec.
+/// Fields take `*`, lists, ranges, `/` steps and month/day names; the
+/// `@hourly`, `@daily`, `@weekly`, `@monthly` and `@yearly` shorthands are
+/// expanded. When both day fields are restricted a day matches either one.
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronExpression {
+    minutes: u64,
+    hours: u64,
+    days_of_month: u64,
+    months: u64,
+    days_of_week: u64,
+    day_of_month_restricted: bool,
+    day_of_week_restricted: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CronParseError(String);
+
+impl fmt::Display for CronParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl std::error::Error for CronParseError {}
+
+impl CronExpression {
+    pub fn parse(expression: &str) -> Result<Self, CronParseError> {
+        let expression = expression.trim();
+        let expanded = match expression.to_ascii_lowercase().as_str() {
+            "@yearly" | "@annually" => "0 0 1 1 *",
+            "@monthly" => "0 0 1 * *",
+            "@weekly" => "0 0 * * 0",
+            "@daily" | "@midnight" => "0 0 * * *",
+            "@hourly" => "0 * * * *",
+            _ => expression,
+        };
+
+        let fields: Vec<&str> = expanded.split_whitespace().collect();
+        if fields.len() != 5 {
+            return Err(CronParseError(format!(
+                "Expected 5 fields (minute hour day-of-month month day-of-week), found {}",
+                fields.len()
+            )));
+        }
+
+        // Day of week accepts 7 as a second spelling of Sunday
+        let mut days_of_week = parse_field(fields[4], 0, 7, &DAY_NAMES, "day of week")?;
+        if days_of_week & (1 << 7) != 0 {
+            days_of_week = (days_of_week & !(1 << 7)) | 1;
+        }
+
+        Ok(Self {
+            minutes: parse_field(fields[0], 0, 59, &[], "minute")?,
+            hours: parse_field(fields[1], 0, 23, &[], "hour")?,
+            days_of_month: parse_field(fields[2], 1, 31, &[], "day of month")?,
+            months: parse_field(fields[3], 1, 12, &MONTH_NAMES, "month")?,
+            days_of_week,
+            day_of_month_restricted: !is_wildcard(fields[2]),
+            day_of_week_restricted: !is_wildcard(fields[4]),
+        })
+    }
+
+    fn matches_day(&self, date: NaiveDate) -> bool {
+        if !has(self.months, date.month()) {
+            return false;
+        }
+
+        let dom = has(self.days_of_month, date.day());
+        let dow = has(self.days_of_week, date.weekday().num_days_from_sunday());
+        match (self.day_of_month_restricted, self.day_of_week_restricted) {
+            (true, true) => dom || dow,
+            (true, false) => dom,
+            (false, true) => dow,
+            (false, false) => true,
+        }
+    }
+
+    /// The first run strictly after `after`, evaluated in `timezone` and
+    /// skipping local dates in `excluded_dates`. A local time that falls in
+    /// a daylight saving gap runs an hour later, so a nightly job still runs
+    /// on the night the clocks change; a repeated time runs once, on its
+    /// first occurrence.
+    pub fn next_after(&self, after: DateTime<Utc>, timezone: Tz, excluded_dates: &[NaiveDate]) -> Option<DateTime<Utc>> {
+        let local_start = after.with_timezone(&timezone).naive_local();
+        let start_date = local_start.date();
+
+        for offset in 0..MAX_LOOKAHEAD_DAYS {
+            let date = start_date + Duration::days(offset);
+            if !self.matches_day(date) || excluded_dates.contains(&date) {
+                continue;
+            }
+
+            for hour in bits(self.hours) {
+                for minute in bits(self.minutes) {
+                    let Some(local) = date.and_hms_opt(hour, minute, 0) else {
+                        continue;
+                    };
+                    if offset == 0 && local < local_start.with_second(0).unwrap_or(local_start) {
+                        continue;
+                    }
+
+                    let Some(candidate) = timezone
+                        .from_local_datetime(&local)
+                        .earliest()
+                        .or_else(|| timezone.from_local_datetime(&(local + Duration::hours(1))).earliest())
+                    else {
+                        continue;
+                    };
+                    let candidate = candidate.with_timezone(&Utc);
+                    if candidate > after {
+                        return Some(candidate);
+                    }
+                }
+            }
+        }
+
+        None
+    }
+
+    /// The next `count` runs after `after`
+    pub fn upcoming(&self, after: DateTime<Utc>, timezone: Tz, excluded_dates: &[NaiveDate], count: usize) -> Vec<DateTime<Utc>> {
+        let mut runs = Vec::with_capacity(count);
+        let mut cursor = after;
+        while runs.len() < count {
+            match self.next_after(cursor, timezone, excluded_dates) {
+                Some(next) => {
+                    runs.push(next);
+                    cursor = next;
+                }
+                None => break,
+            }
+        }
+        runs
+    }
+
+    /// The shortest gap between consecutive runs, sampled over the next
+    /// `samples` runs. A sample of a day or two of runs is enough for
+    /// every expression whose pattern repeats daily; sparser expressions
+    /// have gaps of a day or more anyway.
+    pub fn shortest_interval(&self, after: DateTime<Utc>, timezone: Tz, samples: usize) -> Option<Duration> {
+        self.upcoming(after, timezone, &[], samples)
+            .windows(2)
+            .map(|pair| pair[1] - pair[0])
+            .min()
+    }
+}
+
+fn has(set: u64, value: u32) -> bool {
+    set & (1 << value) != 0
+}
+
+fn bits(set: u64) -> impl Iterator<Item = u32> {
+    (0..64).filter(move |bit| set & (1 << bit) != 0)
+}
+
+fn is_wildcard(field: &str) -> bool {
+    field == "*" || field == "?"
+}
+
+fn parse_value(value: &str, min: u32, max: u32, names: &[&str], field: &str) -> Result<u32, CronParseError> {
+    let parsed = match names.iter().position(|name| name.eq_ignore_ascii_case(value)) {
+        // Month names start at 1, day names at 0
+        Some(index) => index as u32 + min,
+        None => value
+            .parse::<u32>()
+            .map_err(|_| CronParseError(format!("Invalid {} value '{}'", field, value)))?,
+    };
+
+    if parsed < min || parsed > max {
+        return Err(CronParseError(format!(
+            "{} value {} is outside {}-{}",
+            field, parsed, min, max
+        )));
+    }
+    Ok(parsed)
+}
+
+fn parse_field(spec: &str, min: u32, max: u32, names: &[&str], field: &str) -> Result<u64, CronParseError> {
+    let mut set = 0u64;
+
+    for part in spec.split(',') {
+        let (range, step) = match part.split_once('/') {
+            Some((range, step)) => {
+                let step = step
+                    .parse::<u32>()
+                    .ok()
+                    .filter(|step| *step > 0)
+                    .ok_or_else(|| CronParseError(format!("Invalid {} step '{}'", field, step)))?;
+                (range, step)
+            }
+            None => (part, 1),
+        };
+
+        let (start, end) = if is_wildcard(range) {
+            (min, max)
+        } else if let Some((start, end)) = range.split_once('-') {
+            let start = parse_value(start, min, max, names, field)?;
+            let end = parse_value(end, min, max, names, field)?;
+            if start > end {
+                return Err(CronParseError(format!("Invalid {} range '{}'", field, range)));
+            }
+            (start, end)
+        } else {
+            let start = parse_value(range, min, max, names, field)?;
+            // `5/15` means every 15 starting at 5
+            (start, if step > 1 { max } else { start })
+        };
+
+        for value in (start..=end).step_by(step as usize) {
+            set |= 1 << value;
+        }
+    }
+
+    Ok(set)
+}
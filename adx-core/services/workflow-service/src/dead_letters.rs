@@ -0,0 +1,461 @@
+//! Dead-letter queue for workflow runs that failed for good.
+//!
+//! A failed run is captured together with its input and failure. Operators
+//! can then inspect it, correct the input and resubmit it as a new run, or
+//! discard it. Every capture raises an alert through the monitoring module.
+
+use crate::{
+    approvals::require_user,
+    error::{WorkflowServiceError, WorkflowServiceResult},
+    models::*,
+    monitoring::{AlertManager, CostTracker, HealthIssue, IssueSeverity, IssueType},
+    repositories::WorkflowDeadLetterRepository,
+    task_queues::TaskQueueRouter,
+    templates::{TemplateWorkflowInput, TEMPLATE_WORKFLOW_TYPE},
+    transactions::{BusinessTransactionRequest, BUSINESS_TRANSACTION_WORKFLOW_TYPE},
+    visualization::TENANT_INPUT_FIELDS,
+};
+use adx_shared::temporal::{AdxTemporalClient, WorkflowSearchAttributes};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Pending dead letters of one workflow type in a tenant at which captures
+/// are alerted as critical rather than as a warning
+pub const DEAD_LETTER_ALERT_THRESHOLD: u32 = 10;
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+const MAX_PAGE_SIZE: u32 = 200;
+const MAX_BULK_RESUBMIT: usize = 100;
+const MAX_NOTE_LENGTH: usize = 2000;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum DeadLetterStatus {
+    Pending,
+    Resubmitted,
+    Discarded,
+}
+
+impl DeadLetterStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DeadLetterStatus::Pending => "pending",
+            DeadLetterStatus::Resubmitted => "resubmitted",
+            DeadLetterStatus::Discarded => "discarded",
+        }
+    }
+
+    pub fn parse(value: &str) -> WorkflowServiceResult<Self> {
+        match value {
+            "pending" => Ok(DeadLetterStatus::Pending),
+            "resubmitted" => Ok(DeadLetterStatus::Resubmitted),
+            "discarded" => Ok(DeadLetterStatus::Discarded),
+            other => Err(WorkflowServiceError::Internal(format!("Unknown dead letter status: {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DeadLetter {
+    pub dead_letter_id: String,
+    pub tenant_id: String,
+    pub workflow_id: String,
+    pub run_id: Option<String>,
+    pub workflow_type: String,
+    pub task_queue: String,
+    /// Input the next resubmission will use
+    pub input: Value,
+    /// Input the failed run received
+    pub original_input: Value,
+    pub failure_message: String,
+    pub failure_details: Value,
+    pub started_by: Option<String>,
+    pub status: DeadLetterStatus,
+    pub input_edited_by: Option<String>,
+    pub input_edited_at: Option<DateTime<Utc>>,
+    pub resubmit_count: u32,
+    pub resubmitted_workflow_id: Option<String>,
+    pub resolved_by: Option<String>,
+    pub resolution_note: Option<String>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub failed_at: DateTime<Utc>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CaptureDeadLetterRequest {
+    pub tenant_id: String,
+    pub workflow_id: String,
+    pub run_id: Option<String>,
+    pub workflow_type: String,
+    pub task_queue: String,
+    pub input: Value,
+    pub failure_message: String,
+    #[serde(default)]
+    pub failure_details: Value,
+    pub started_by: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DeadLetterListParams {
+    pub status: Option<DeadLetterStatus>,
+    pub workflow_type: Option<String>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeadLetterListResponse {
+    pub dead_letters: Vec<DeadLetter>,
+    pub total_count: u64,
+    pub limit: u32,
+    pub offset: u32,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct UpdateDeadLetterInputRequest {
+    pub input: Value,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct DiscardDeadLetterRequest {
+    pub note: Option<String>,
+}
+
+/// Resubmit the listed dead letters, or else the oldest pending ones of a
+/// workflow type
+#[derive(Debug, Deserialize)]
+pub struct BulkResubmitRequest {
+    #[serde(default)]
+    pub dead_letter_ids: Vec<String>,
+    pub workflow_type: Option<String>,
+    pub limit: Option<usize>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkResubmitFailure {
+    pub dead_letter_id: String,
+    pub error: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct BulkResubmitResponse {
+    pub resubmitted: Vec<DeadLetter>,
+    pub failed: Vec<BulkResubmitFailure>,
+}
+
+pub struct DeadLetterQueue {
+    repository: Arc<dyn WorkflowDeadLetterRepository>,
+    temporal: AdxTemporalClient,
+    alerts: Arc<AlertManager>,
+    task_queues: Arc<TaskQueueRouter>,
+    costs: Arc<CostTracker>,
+}
+
+impl DeadLetterQueue {
+    pub fn new(
+        repository: Arc<dyn WorkflowDeadLetterRepository>,
+        temporal: AdxTemporalClient,
+        alerts: Arc<AlertManager>,
+        task_queues: Arc<TaskQueueRouter>,
+        costs: Arc<CostTracker>,
+    ) -> Self {
+        Self {
+            repository,
+            temporal,
+            alerts,
+            task_queues,
+            costs,
+        }
+    }
+
+    /// Capture a failed run. Capturing the same workflow again returns the
+    /// existing entry without alerting twice.
+    pub async fn capture(&self, request: CaptureDeadLetterRequest) -> WorkflowServiceResult<DeadLetter> {
+        let (dead_letter, created) = self.repository.capture(&request).await?;
+        if !created {
+            return Ok(dead_letter);
+        }
+
+        warn!(
+            "Workflow {} ({}) dead-lettered for tenant {}: {}",
+            dead_letter.workflow_id, dead_letter.workflow_type, dead_letter.tenant_id, dead_letter.failure_message
+        );
+
+        let pending = self
+            .repository
+            .count_pending(&dead_letter.tenant_id, &dead_letter.workflow_type)
+            .await?;
+        let issue = HealthIssue {
+            issue_id: Uuid::new_v4().to_string(),
+            workflow_id: dead_letter.workflow_id.clone(),
+            issue_type: IssueType::DeadLettered,
+            severity: if pending >= DEAD_LETTER_ALERT_THRESHOLD {
+                IssueSeverity::Critical
+            } else {
+                IssueSeverity::Warning
+            },
+            message: format!(
+                "{} failed and was dead-lettered ({} pending for tenant {}): {}",
+                dead_letter.workflow_type, pending, dead_letter.tenant_id, dead_letter.failure_message
+            ),
+            detected_at: dead_letter.failed_at,
+            suggested_actions: vec![
+                format!("Inspect dead letter {}", dead_letter.dead_letter_id),
+                "Correct the input and resubmit, or discard it".to_string(),
+            ],
+        };
+        if let Err(error) = self.alerts.trigger_alert(&issue).await {
+            warn!("Failed to raise dead letter alert for {}: {}", dead_letter.workflow_id, error);
+        }
+
+        Ok(dead_letter)
+    }
+
+    pub async fn list(&self, tenant_id: &str, params: DeadLetterListParams) -> WorkflowServiceResult<DeadLetterListResponse> {
+        let limit = params.limit.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_PAGE_SIZE);
+        let offset = params.offset.unwrap_or(0);
+        let (dead_letters, total_count) = self
+            .repository
+            .list(tenant_id, params.status, params.workflow_type.as_deref(), limit, offset)
+            .await?;
+
+        Ok(DeadLetterListResponse {
+            dead_letters,
+            total_count,
+            limit,
+            offset,
+        })
+    }
+
+    pub async fn get(&self, tenant_id: &str, dead_letter_id: &str) -> WorkflowServiceResult<DeadLetter> {
+        self.repository
+            .get(tenant_id, dead_letter_id)
+            .await?
+            .ok_or_else(|| WorkflowServiceError::NotFound(format!("Dead letter {} not found", dead_letter_id)))
+    }
+
+    /// Replace the input the next resubmission uses. The original input is kept.
+    pub async fn update_input(
+        &self,
+        tenant_id: &str,
+        user_id: Option<&str>,
+        dead_letter_id: &str,
+        request: UpdateDeadLetterInputRequest,
+    ) -> WorkflowServiceResult<DeadLetter> {
+        let user_id = require_user(user_id)?;
+        let dead_letter = self.get(tenant_id, dead_letter_id).await?;
+        require_pending(&dead_letter)?;
+        validate_input(&dead_letter, &request.input)?;
+
+        let dead_letter = self
+            .repository
+            .update_input(tenant_id, dead_letter_id, &request.input, user_id)
+            .await?
+            .ok_or_else(|| closed(dead_letter_id))?;
+
+        info!("Dead letter {} input edited by {}", dead_letter_id, user_id);
+        Ok(dead_letter)
+    }
+
+    /// Start a new run with the dead letter's current input
+    pub async fn resubmit(
+        &self,
+        tenant_id: &str,
+        user_id: Option<&str>,
+        dead_letter_id: &str,
+    ) -> WorkflowServiceResult<DeadLetter> {
+        let user_id = require_user(user_id)?;
+        let dead_letter = self.get(tenant_id, dead_letter_id).await?;
+        require_pending(&dead_letter)?;
+        self.costs.check_start(tenant_id, &dead_letter.workflow_type).await?;
+
+        let workflow_id = format!("{}-r{}", dead_letter.workflow_id, dead_letter.resubmit_count + 1);
+        let input = resubmission_input(&dead_letter, &workflow_id);
+        // Routed afresh, as the tenant may have moved queues since the failure
+        let task_queue = self.task_queues.route(tenant_id, &dead_letter.workflow_type).await?;
+
+        // Claim the entry before starting so concurrent resubmits start one run
+        let claimed = self
+            .repository
+            .mark_resubmitted(tenant_id, dead_letter_id, &workflow_id, user_id)
+            .await?
+            .ok_or_else(|| closed(dead_letter_id))?;
+
+        if let Err(error) = self
+            .temporal
+            .start_workflow_with_search_attributes::<Value, Value>(
+                &dead_letter.workflow_type,
+                workflow_id.clone(),
+                &task_queue,
+                input,
+                &WorkflowSearchAttributes::new(tenant_id, &dead_letter.workflow_type)
+                    .user(Some(user_id))
+                    .business_key(format!("dead_letter:{}", dead_letter_id))
+                    .lane(self.task_queues.lane(&dead_letter.workflow_type, None)),
+            )
+            .await
+        {
+            self.repository
+                .release_resubmission(tenant_id, dead_letter_id, &workflow_id)
+                .await?;
+            return Err(WorkflowServiceError::Temporal(error.to_string()));
+        }
+
+        info!("Dead letter {} resubmitted as {} by {}", dead_letter_id, workflow_id, user_id);
+        Ok(claimed)
+    }
+
+    /// Resubmit entries one by one; a failure doesn't stop the rest
+    pub async fn bulk_resubmit(
+        &self,
+        tenant_id: &str,
+        user_id: Option<&str>,
+        request: BulkResubmitRequest,
+    ) -> WorkflowServiceResult<BulkResubmitResponse> {
+        require_user(user_id)?;
+        let limit = request.limit.unwrap_or(MAX_BULK_RESUBMIT).clamp(1, MAX_BULK_RESUBMIT);
+
+        let dead_letter_ids = if !request.dead_letter_ids.is_empty() {
+            if request.dead_letter_ids.len() > MAX_BULK_RESUBMIT {
+                return Err(WorkflowServiceError::Validation(format!(
+                    "At most {} dead letters can be resubmitted at once",
+                    MAX_BULK_RESUBMIT
+                )));
+            }
+            request.dead_letter_ids
+        } else if let Some(workflow_type) = request.workflow_type.as_deref() {
+            self.repository
+                .oldest_pending(tenant_id, workflow_type, limit as u32)
+                .await?
+        } else {
+            return Err(WorkflowServiceError::Validation(
+                "Either dead_letter_ids or workflow_type is required".to_string(),
+            ));
+        };
+
+        let mut response = BulkResubmitResponse {
+            resubmitted: Vec::new(),
+            failed: Vec::new(),
+        };
+        for dead_letter_id in dead_letter_ids {
+            match self.resubmit(tenant_id, user_id, &dead_letter_id).await {
+                Ok(dead_letter) => response.resubmitted.push(dead_letter),
+                Err(error) => response.failed.push(BulkResubmitFailure {
+                    dead_letter_id,
+                    error: error.to_string(),
+                }),
+            }
+        }
+
+        info!(
+            "Bulk resubmit for tenant {}: {} resubmitted, {} failed",
+            tenant_id,
+            response.resubmitted.len(),
+            response.failed.len()
+        );
+        Ok(response)
+    }
+
+    pub async fn discard(
+        &self,
+        tenant_id: &str,
+        user_id: Option<&str>,
+        dead_letter_id: &str,
+        request: DiscardDeadLetterRequest,
+    ) -> WorkflowServiceResult<DeadLetter> {
+        let user_id = require_user(user_id)?;
+        let note = request.note.map(|n| n.trim().to_string()).filter(|n| !n.is_empty());
+        if note.as_ref().is_some_and(|n| n.chars().count() > MAX_NOTE_LENGTH) {
+            return Err(WorkflowServiceError::Validation(format!(
+                "Notes are limited to {} characters",
+                MAX_NOTE_LENGTH
+            )));
+        }
+
+        let dead_letter = self.get(tenant_id, dead_letter_id).await?;
+        require_pending(&dead_letter)?;
+
+        let dead_letter = self
+            .repository
+            .discard(tenant_id, dead_letter_id, user_id, note.as_deref())
+            .await?
+            .ok_or_else(|| closed(dead_letter_id))?;
+
+        info!("Dead letter {} discarded by {}", dead_letter_id, user_id);
+        Ok(dead_letter)
+    }
+}
+
+fn require_pending(dead_letter: &DeadLetter) -> WorkflowServiceResult<()> {
+    if dead_letter.status != DeadLetterStatus::Pending {
+        return Err(WorkflowServiceError::DeadLetterResolved(format!(
+            "Dead letter {} is already {}",
+            dead_letter.dead_letter_id,
+            dead_letter.status.as_str()
+        )));
+    }
+    Ok(())
+}
+
+fn closed(dead_letter_id: &str) -> WorkflowServiceError {
+    WorkflowServiceError::DeadLetterResolved(format!("Dead letter {} was resolved concurrently", dead_letter_id))
+}
+
+/// Edited input must still be a valid request for the workflow and may not
+/// move it to another tenant. Template runs only take new parameters, so an
+/// edit cannot bypass template validation.
+fn validate_input(dead_letter: &DeadLetter, input: &Value) -> WorkflowServiceResult<()> {
+    if !input.is_object() {
+        return Err(WorkflowServiceError::Validation("Input must be a JSON object".to_string()));
+    }
+
+    let workflow_type = dead_letter.workflow_type.as_str();
+    let parsed = match workflow_type {
+        "user_onboarding_workflow" => serde_json::from_value::<UserOnboardingRequest>(input.clone()).map(|_| ()),
+        "tenant_switching_workflow" => serde_json::from_value::<TenantSwitchingRequest>(input.clone()).map(|_| ()),
+        "data_migration_workflow" => serde_json::from_value::<DataMigrationRequest>(input.clone()).map(|_| ()),
+        "bulk_operation_workflow" => serde_json::from_value::<BulkOperationRequest>(input.clone()).map(|_| ()),
+        "compliance_workflow" => serde_json::from_value::<ComplianceWorkflowRequest>(input.clone()).map(|_| ()),
+        TEMPLATE_WORKFLOW_TYPE => serde_json::from_value::<TemplateWorkflowInput>(input.clone()).map(|_| ()),
+        BUSINESS_TRANSACTION_WORKFLOW_TYPE => serde_json::from_value::<BusinessTransactionRequest>(input.clone()).map(|_| ()),
+        _ => Ok(()),
+    };
+    parsed.map_err(|e| WorkflowServiceError::Validation(format!("Invalid input for {}: {}", workflow_type, e)))?;
+
+    if workflow_type == TEMPLATE_WORKFLOW_TYPE
+        && ["workflow_id", "template_id", "template_version", "user_id", "definition"]
+            .into_iter()
+            .any(|field| input.get(field) != dead_letter.original_input.get(field))
+    {
+        return Err(WorkflowServiceError::Validation(
+            "Only the parameters of a template run can be edited".to_string(),
+        ));
+    }
+
+    if TENANT_INPUT_FIELDS
+        .iter()
+        .any(|field| input.get(field) != dead_letter.original_input.get(field))
+    {
+        return Err(WorkflowServiceError::Authorization(
+            "The tenants a dead-lettered workflow acts on cannot be changed".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+/// Template runs carry their own workflow id in the input
+fn resubmission_input(dead_letter: &DeadLetter, workflow_id: &str) -> Value {
+    let mut input = dead_letter.input.clone();
+    if let Some(object) = input.as_object_mut() {
+        if object.get("workflow_id").and_then(Value::as_str) == Some(dead_letter.workflow_id.as_str()) {
+            object.insert("workflow_id".to_string(), Value::String(workflow_id.to_string()));
+        }
+    }
+    input
+}
@@ -76,12 +76,33 @@ pub enum WorkflowServiceError {
     #[error("Invalid parameter: {0}")]
     InvalidParameter(String),
 
+    #[error("Approval rejected: {0}")]
+    ApprovalRejected(String),
+
+    #[error("Approval timed out: {0}")]
+    ApprovalTimeout(String),
+
+    #[error("Approval closed: {0}")]
+    ApprovalClosed(String),
+
+    #[error("Dead letter resolved: {0}")]
+    DeadLetterResolved(String),
+
+    #[error("Workflow cancelled: {0}")]
+    WorkflowCancelled(String),
+
     #[error("Migration error: {0}")]
     Migration(String),
 
     #[error("Monitoring error: {0}")]
     Monitoring(String),
 
+    #[error("Not found: {0}")]
+    NotFound(String),
+
+    #[error("Limit exceeded: {0}")]
+    LimitExceeded(String),
+
     #[error("Internal server error: {0}")]
     Internal(String),
 }
@@ -92,6 +113,16 @@ impl IntoResponse for WorkflowServiceError {
             WorkflowServiceError::Validation(_) => (StatusCode::BAD_REQUEST, self.to_string()),
             WorkflowServiceError::Authorization(_) => (StatusCode::FORBIDDEN, self.to_string()),
             WorkflowServiceError::TenantContext(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            WorkflowServiceError::NotFound(_) => (StatusCode::NOT_FOUND, self.to_string()),
+            WorkflowServiceError::LimitExceeded(_) => (StatusCode::FORBIDDEN, self.to_string()),
+            WorkflowServiceError::InvalidTemplate(_)
+            | WorkflowServiceError::InvalidVersion(_)
+            | WorkflowServiceError::MissingParameter(_)
+            | WorkflowServiceError::InvalidParameter(_) => (StatusCode::BAD_REQUEST, self.to_string()),
+            WorkflowServiceError::TemplateInUse(_)
+            | WorkflowServiceError::ApprovalClosed(_)
+            | WorkflowServiceError::DeadLetterResolved(_)
+            | WorkflowServiceError::WorkflowCancelled(_) => (StatusCode::CONFLICT, self.to_string()),
             WorkflowServiceError::ServiceCommunication { .. } => {
                 (StatusCode::BAD_GATEWAY, self.to_string())
             }
@@ -1,22 +1,36 @@
 use crate::{
     activities::{CrossServiceActivities, CrossServiceActivitiesImpl, CreateBackupRequest, RestoreBackupRequest},
+    approvals::{ApprovalManager, ApprovalDecision, ApprovalInboxParams, ApprovalInboxResponse, ApprovalTask, DecideApprovalRequest},
+    batches::{WorkflowBatchManager, SubmitBatchRequest, WorkflowBatch, BatchChildrenParams, BatchChildrenResponse},
     config::WorkflowServiceConfig,
+    controls::{WorkflowControlManager, RunControlRegistry, ControlAction, WorkflowControlRequest, WorkflowControlResponse, WorkflowControlHistory},
+    dead_letters::{DeadLetterQueue, CaptureDeadLetterRequest, DeadLetter, DeadLetterListParams, DeadLetterListResponse, UpdateDeadLetterInputRequest, DiscardDeadLetterRequest, BulkResubmitRequest, BulkResubmitResponse},
     error::{WorkflowServiceError, WorkflowServiceResult},
+    interactions::{WorkflowInteractionManager, Caller, InteractionParams, WorkflowInteractionsResponse, SignalWorkflowResponse, QueryWorkflowResponse},
     management::{WorkflowManager, CancelWorkflowRequest, RetryWorkflowRequest, TerminateWorkflowRequest, BulkWorkflowOperationRequest},
     models::*,
-    monitoring::{WorkflowMonitor, AnalyticsParams, TimeRange},
+    monitoring::{WorkflowMonitor, AnalyticsParams, TimeRange, CostTracker, RunCostMeter, RunCost, CostReport, CostReportParams, BudgetStatus, SetWorkflowBudgetRequest, SlaMonitor, WorkflowSla, SetWorkflowSlaRequest, SlaStatusResponse, SlaBreachParams, SlaBreachListResponse},
+    schedules::{WorkflowScheduleManager, CreateScheduleRequest, UpdateScheduleRequest, PauseScheduleRequest, CreateCalendarRequest, UpdateCalendarRequest, SetScheduleLimitsRequest},
+    search::{WorkflowSearch, ListWorkflowsParams, ListWorkflowsResponse, InFlightParams, InFlightWorkflows},
     server::TenantContext,
-    templates::{WorkflowTemplateManager, CreateTemplateRequest, GetTemplatesParams, CreateFromTemplateRequest, UpdateTemplateRequest, PatternAnalysisParams, GenerateTemplateRequest},
-    versioning::{WorkflowVersionManager, RegisterVersionRequest, MigrateWorkflowsRequest, RollbackMigrationRequest, DeprecateVersionRequest},
+    task_queues::{TaskQueueRouter, SetTaskQueueAssignmentRequest, TaskQueueRoutingResponse},
+    templates::{WorkflowTemplateManager, TEMPLATE_WORKFLOW_TYPE, parse_document, DocumentFormat, GetTemplatesParams, InstantiateTemplateRequest, PatternAnalysisParams, GenerateTemplateRequest},
+    transactions::{business_transaction_workflow, BusinessTransactionRequest, BUSINESS_TRANSACTION_WORKFLOW_TYPE},
+    triggers::{WorkflowTriggerManager, WorkflowTrigger, CreateTriggerRequest, UpdateTriggerRequest, ListTriggersResponse, TriggerFiringParams, TriggerFiringsResponse, PublishEventRequest, PublishEventResponse, TriggerEventDetails},
+    versioning::{WorkflowVersionManager, RegisterVersionRequest, MigrateWorkflowsRequest, RollbackMigrationRequest, DeprecateVersionRequest, OutdatedExecutionsParams},
+    visualization::{ExecutionHistoryManager, ExecutionGraph, ExecutionGraphParams, ExecutionHistoryExport},
     workflows::*,
 };
 use axum::{
+    body::Bytes,
     extract::{Extension, Path, Query},
     response::Json,
-    http::StatusCode,
+    http::{header, HeaderMap, StatusCode},
 };
 use chrono::Utc;
 use serde::{Deserialize, Serialize};
+use adx_shared::temporal::{PriorityLane, WorkflowPriority};
+use adx_shared::types::{WorkflowApiResponse, WorkflowStatusResponse as OperationStatusResponse};
 use std::{collections::HashMap, sync::Arc};
 use tracing::{info, warn, error};
 use uuid::Uuid;
@@ -25,111 +39,282 @@ use uuid::Uuid;
 
 pub async fn start_user_onboarding_workflow(
     Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(dead_letters): Extension<Arc<DeadLetterQueue>>,
+    Extension(task_queues): Extension<Arc<TaskQueueRouter>>,
+    Extension(costs): Extension<Arc<CostTracker>>,
+    Extension(slas): Extension<Arc<SlaMonitor>>,
+    Extension(runs): Extension<Arc<RunControlRegistry>>,
     Extension(tenant_context): Extension<TenantContext>,
+    Query(params): Query<StartWorkflowParams>,
     Json(request): Json<UserOnboardingRequest>,
 ) -> WorkflowServiceResult<Json<WorkflowStartResponse>> {
     info!("Starting user onboarding workflow for email: {}", request.user_email);
     
+    costs.check_start(&tenant_context.tenant_id, "user_onboarding_workflow").await?;
+    let lane = task_queues.lane("user_onboarding_workflow", params.priority);
     let workflow_id = format!("user_onboarding_{}", Uuid::new_v4());
-    let activities = CrossServiceActivitiesImpl::new((*config).clone());
+    let meter = Arc::new(RunCostMeter::new());
+    let activities = CrossServiceActivitiesImpl::new((*config).clone())
+        .with_meter(meter.clone())
+        .with_control(runs.register(&tenant_context.tenant_id, &workflow_id, "user_onboarding_workflow", tenant_context.user_id.as_deref()).in_lane(lane));
+    let input = serde_json::to_value(&request)?;
     
     // For now, execute workflow synchronously
     // In a real implementation, this would be submitted to Temporal
-    let result = user_onboarding_workflow(request, &activities).await?;
+    let result = user_onboarding_workflow(request, &activities).await;
+    record_finished_run(&costs, &slas, &tenant_context, &workflow_id, "user_onboarding_workflow", &meter, result.is_ok()).await;
+    let result = dead_letter_on_failure(&dead_letters, &task_queues, &tenant_context, &workflow_id, "user_onboarding_workflow", input, result).await?;
     
     Ok(Json(WorkflowStartResponse {
         workflow_id: workflow_id.clone(),
         status: "completed".to_string(),
         result: Some(serde_json::to_value(result)?),
         started_at: Utc::now(),
+        lane,
     }))
 }
 
 pub async fn start_tenant_switching_workflow(
     Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(dead_letters): Extension<Arc<DeadLetterQueue>>,
+    Extension(task_queues): Extension<Arc<TaskQueueRouter>>,
+    Extension(costs): Extension<Arc<CostTracker>>,
+    Extension(slas): Extension<Arc<SlaMonitor>>,
+    Extension(runs): Extension<Arc<RunControlRegistry>>,
     Extension(tenant_context): Extension<TenantContext>,
+    Query(params): Query<StartWorkflowParams>,
     Json(request): Json<TenantSwitchingRequest>,
 ) -> WorkflowServiceResult<Json<WorkflowStartResponse>> {
     info!("Starting tenant switching workflow for user: {}", request.user_id);
     
+    costs.check_start(&tenant_context.tenant_id, "tenant_switching_workflow").await?;
+    let lane = task_queues.lane("tenant_switching_workflow", params.priority);
     let workflow_id = format!("tenant_switching_{}", Uuid::new_v4());
-    let activities = CrossServiceActivitiesImpl::new((*config).clone());
+    let meter = Arc::new(RunCostMeter::new());
+    let activities = CrossServiceActivitiesImpl::new((*config).clone())
+        .with_meter(meter.clone())
+        .with_control(runs.register(&tenant_context.tenant_id, &workflow_id, "tenant_switching_workflow", tenant_context.user_id.as_deref()).in_lane(lane));
+    let input = serde_json::to_value(&request)?;
     
     // Execute workflow
-    let result = tenant_switching_workflow(request, &activities).await?;
+    let result = tenant_switching_workflow(request, &activities).await;
+    record_finished_run(&costs, &slas, &tenant_context, &workflow_id, "tenant_switching_workflow", &meter, result.is_ok()).await;
+    let result = dead_letter_on_failure(&dead_letters, &task_queues, &tenant_context, &workflow_id, "tenant_switching_workflow", input, result).await?;
     
     Ok(Json(WorkflowStartResponse {
         workflow_id: workflow_id.clone(),
         status: "completed".to_string(),
         result: Some(serde_json::to_value(result)?),
         started_at: Utc::now(),
+        lane,
     }))
 }
 
 pub async fn start_data_migration_workflow(
     Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(dead_letters): Extension<Arc<DeadLetterQueue>>,
+    Extension(task_queues): Extension<Arc<TaskQueueRouter>>,
+    Extension(costs): Extension<Arc<CostTracker>>,
+    Extension(slas): Extension<Arc<SlaMonitor>>,
+    Extension(runs): Extension<Arc<RunControlRegistry>>,
     Extension(tenant_context): Extension<TenantContext>,
+    Query(params): Query<StartWorkflowParams>,
     Json(request): Json<DataMigrationRequest>,
 ) -> WorkflowServiceResult<Json<WorkflowStartResponse>> {
     info!("Starting data migration workflow: {}", request.migration_id);
     
+    costs.check_start(&tenant_context.tenant_id, "data_migration_workflow").await?;
+    let lane = task_queues.lane("data_migration_workflow", params.priority);
     let workflow_id = format!("data_migration_{}", Uuid::new_v4());
-    let activities = CrossServiceActivitiesImpl::new((*config).clone());
+    let meter = Arc::new(RunCostMeter::new());
+    let activities = CrossServiceActivitiesImpl::new((*config).clone())
+        .with_meter(meter.clone())
+        .with_control(runs.register(&tenant_context.tenant_id, &workflow_id, "data_migration_workflow", tenant_context.user_id.as_deref()).in_lane(lane));
+    let input = serde_json::to_value(&request)?;
     
     // For large migrations, this would be submitted to Temporal as async
     // For now, execute synchronously
-    let result = data_migration_workflow(request, &activities).await?;
+    let result = data_migration_workflow(request, &activities).await;
+    record_finished_run(&costs, &slas, &tenant_context, &workflow_id, "data_migration_workflow", &meter, result.is_ok()).await;
+    let result = dead_letter_on_failure(&dead_letters, &task_queues, &tenant_context, &workflow_id, "data_migration_workflow", input, result).await?;
     
     Ok(Json(WorkflowStartResponse {
         workflow_id: workflow_id.clone(),
         status: "completed".to_string(),
         result: Some(serde_json::to_value(result)?),
         started_at: Utc::now(),
+        lane,
     }))
 }
 
 pub async fn start_bulk_operation_workflow(
     Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(dead_letters): Extension<Arc<DeadLetterQueue>>,
+    Extension(task_queues): Extension<Arc<TaskQueueRouter>>,
+    Extension(costs): Extension<Arc<CostTracker>>,
+    Extension(slas): Extension<Arc<SlaMonitor>>,
+    Extension(runs): Extension<Arc<RunControlRegistry>>,
     Extension(tenant_context): Extension<TenantContext>,
+    Query(params): Query<StartWorkflowParams>,
     Json(request): Json<BulkOperationRequest>,
 ) -> WorkflowServiceResult<Json<WorkflowStartResponse>> {
     info!("Starting bulk operation workflow: {}", request.operation_id);
     
+    costs.check_start(&tenant_context.tenant_id, "bulk_operation_workflow").await?;
+    let lane = task_queues.lane("bulk_operation_workflow", params.priority);
     let workflow_id = format!("bulk_operation_{}", Uuid::new_v4());
-    let activities = CrossServiceActivitiesImpl::new((*config).clone());
+    let meter = Arc::new(RunCostMeter::new());
+    let activities = CrossServiceActivitiesImpl::new((*config).clone())
+        .with_meter(meter.clone())
+        .with_control(runs.register(&tenant_context.tenant_id, &workflow_id, "bulk_operation_workflow", tenant_context.user_id.as_deref()).in_lane(lane));
+    let input = serde_json::to_value(&request)?;
     
     // Execute workflow
-    let result = bulk_operation_workflow(request, &activities).await?;
+    let result = bulk_operation_workflow(request, &activities).await;
+    record_finished_run(&costs, &slas, &tenant_context, &workflow_id, "bulk_operation_workflow", &meter, result.is_ok()).await;
+    let result = dead_letter_on_failure(&dead_letters, &task_queues, &tenant_context, &workflow_id, "bulk_operation_workflow", input, result).await?;
     
     Ok(Json(WorkflowStartResponse {
         workflow_id: workflow_id.clone(),
         status: "completed".to_string(),
         result: Some(serde_json::to_value(result)?),
         started_at: Utc::now(),
+        lane,
     }))
 }
 
 pub async fn start_compliance_workflow(
     Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(dead_letters): Extension<Arc<DeadLetterQueue>>,
+    Extension(task_queues): Extension<Arc<TaskQueueRouter>>,
+    Extension(costs): Extension<Arc<CostTracker>>,
+    Extension(slas): Extension<Arc<SlaMonitor>>,
+    Extension(runs): Extension<Arc<RunControlRegistry>>,
     Extension(tenant_context): Extension<TenantContext>,
+    Query(params): Query<StartWorkflowParams>,
     Json(request): Json<ComplianceWorkflowRequest>,
 ) -> WorkflowServiceResult<Json<WorkflowStartResponse>> {
     info!("Starting compliance workflow: {}", request.compliance_id);
     
+    costs.check_start(&tenant_context.tenant_id, "compliance_workflow").await?;
+    let lane = task_queues.lane("compliance_workflow", params.priority);
     let workflow_id = format!("compliance_{}", Uuid::new_v4());
-    let activities = CrossServiceActivitiesImpl::new((*config).clone());
+    let meter = Arc::new(RunCostMeter::new());
+    let activities = CrossServiceActivitiesImpl::new((*config).clone())
+        .with_meter(meter.clone())
+        .with_control(runs.register(&tenant_context.tenant_id, &workflow_id, "compliance_workflow", tenant_context.user_id.as_deref()).in_lane(lane));
+    let input = serde_json::to_value(&request)?;
     
     // Execute workflow
-    let result = compliance_workflow(request, &activities).await?;
+    let result = compliance_workflow(request, &activities).await;
+    record_finished_run(&costs, &slas, &tenant_context, &workflow_id, "compliance_workflow", &meter, result.is_ok()).await;
+    let result = dead_letter_on_failure(&dead_letters, &task_queues, &tenant_context, &workflow_id, "compliance_workflow", input, result).await?;
     
     Ok(Json(WorkflowStartResponse {
         workflow_id: workflow_id.clone(),
         status: "completed".to_string(),
         result: Some(serde_json::to_value(result)?),
         started_at: Utc::now(),
+        lane,
     }))
 }
 
+pub async fn start_business_transaction_workflow(
+    Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(dead_letters): Extension<Arc<DeadLetterQueue>>,
+    Extension(task_queues): Extension<Arc<TaskQueueRouter>>,
+    Extension(costs): Extension<Arc<CostTracker>>,
+    Extension(slas): Extension<Arc<SlaMonitor>>,
+    Extension(runs): Extension<Arc<RunControlRegistry>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Query(params): Query<StartWorkflowParams>,
+    Json(request): Json<BusinessTransactionRequest>,
+) -> WorkflowServiceResult<Json<WorkflowStartResponse>> {
+    info!("Starting business transaction: {}", request.operation());
+    
+    costs.check_start(&tenant_context.tenant_id, BUSINESS_TRANSACTION_WORKFLOW_TYPE).await?;
+    let lane = task_queues.lane(BUSINESS_TRANSACTION_WORKFLOW_TYPE, params.priority);
+    let workflow_id = format!("{}_{}", request.operation(), Uuid::new_v4());
+    let meter = Arc::new(RunCostMeter::new());
+    let activities = CrossServiceActivitiesImpl::new((*config).clone())
+        .with_meter(meter.clone())
+        .with_control(runs.register(&tenant_context.tenant_id, &workflow_id, BUSINESS_TRANSACTION_WORKFLOW_TYPE, tenant_context.user_id.as_deref()).in_lane(lane));
+    let input = serde_json::to_value(&request)?;
+    
+    // Execute workflow; completed steps are compensated if a later one fails
+    let result = business_transaction_workflow(request, &activities).await;
+    record_finished_run(&costs, &slas, &tenant_context, &workflow_id, BUSINESS_TRANSACTION_WORKFLOW_TYPE, &meter, result.is_ok()).await;
+    let result = dead_letter_on_failure(&dead_letters, &task_queues, &tenant_context, &workflow_id, BUSINESS_TRANSACTION_WORKFLOW_TYPE, input, result).await?;
+    
+    Ok(Json(WorkflowStartResponse {
+        workflow_id: workflow_id.clone(),
+        status: "completed".to_string(),
+        result: Some(serde_json::to_value(result)?),
+        started_at: Utc::now(),
+        lane,
+    }))
+}
+
+/// Inline runs leave no Temporal history behind, so a failed run is captured
+/// in the dead-letter queue before its error is returned
+async fn dead_letter_on_failure<T>(
+    dead_letters: &DeadLetterQueue,
+    task_queues: &TaskQueueRouter,
+    tenant_context: &TenantContext,
+    workflow_id: &str,
+    workflow_type: &str,
+    input: serde_json::Value,
+    result: WorkflowServiceResult<T>,
+) -> WorkflowServiceResult<T> {
+    if let Err(error) = &result {
+        // A cancelled run was stopped on purpose and isn't worth replaying
+        if matches!(error, WorkflowServiceError::WorkflowCancelled(_)) {
+            return result;
+        }
+        let task_queue = match task_queues.route(&tenant_context.tenant_id, workflow_type).await {
+            Ok(task_queue) => task_queue,
+            Err(route_error) => {
+                warn!("Failed to route workflow {} for dead-lettering: {}", workflow_id, route_error);
+                task_queues.default_queue(workflow_type)
+            }
+        };
+        let request = CaptureDeadLetterRequest {
+            tenant_id: tenant_context.tenant_id.clone(),
+            workflow_id: workflow_id.to_string(),
+            run_id: None,
+            workflow_type: workflow_type.to_string(),
+            task_queue,
+            input,
+            failure_message: error.to_string(),
+            failure_details: serde_json::json!({ "error": format!("{:?}", error) }),
+            started_by: tenant_context.user_id.clone(),
+        };
+        if let Err(capture_error) = dead_letters.capture(request).await {
+            warn!("Failed to dead-letter workflow {}: {}", workflow_id, capture_error);
+        }
+    }
+    result
+}
+
+/// Cost tracking and SLA checks never fail a run; a run that can't be
+/// recorded or checked is logged
+async fn record_finished_run(
+    costs: &CostTracker,
+    slas: &SlaMonitor,
+    tenant_context: &TenantContext,
+    workflow_id: &str,
+    workflow_type: &str,
+    meter: &RunCostMeter,
+    succeeded: bool,
+) {
+    if let Err(error) = costs.record_run(&tenant_context.tenant_id, workflow_id, workflow_type, meter, succeeded).await {
+        warn!("Failed to record cost of workflow {}: {}", workflow_id, error);
+    }
+    if let Err(error) = slas.run_finished(&tenant_context.tenant_id, workflow_type).await {
+        warn!("Failed to check SLA of {} after workflow {}: {}", workflow_type, workflow_id, error);
+    }
+}
+
 // Workflow management handlers
 
 pub async fn get_workflow_status(
@@ -163,18 +348,19 @@ pub async fn get_workflow_status(
 }
 
 pub async fn cancel_workflow(
-    Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(controls): Extension<Arc<WorkflowControlManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
     Path(workflow_id): Path<String>,
-) -> WorkflowServiceResult<Json<WorkflowCancelResponse>> {
+    request: Option<Json<WorkflowControlRequest>>,
+) -> WorkflowServiceResult<Json<WorkflowControlResponse>> {
     info!("Cancelling workflow: {}", workflow_id);
     
-    // In a real implementation, this would cancel the Temporal workflow
-    Ok(Json(WorkflowCancelResponse {
-        workflow_id,
-        cancelled: true,
-        cancelled_at: Utc::now(),
-        message: "Workflow cancellation requested".to_string(),
-    }))
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+    let response = controls
+        .control(&caller(&tenant_context), &workflow_id, ControlAction::Cancel, request)
+        .await?;
+    
+    Ok(Json(response))
 }
 
 pub async fn retry_workflow(
@@ -194,41 +380,25 @@ pub async fn retry_workflow(
 }
 
 pub async fn list_workflows(
-    Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(search): Extension<Arc<WorkflowSearch>>,
     Extension(tenant_context): Extension<TenantContext>,
     Query(params): Query<ListWorkflowsParams>,
 ) -> WorkflowServiceResult<Json<ListWorkflowsResponse>> {
     info!("Listing workflows for tenant: {}", tenant_context.tenant_id);
     
-    // In a real implementation, this would query Temporal for workflows
-    let workflows = vec![
-        WorkflowSummary {
-            workflow_id: "user_onboarding_123".to_string(),
-            workflow_type: "user_onboarding".to_string(),
-            status: WorkflowExecutionStatus::Completed,
-            started_at: Utc::now() - chrono::Duration::hours(2),
-            updated_at: Utc::now() - chrono::Duration::hours(1),
-            tenant_id: tenant_context.tenant_id.clone(),
-            user_id: tenant_context.user_id.clone(),
-        },
-        WorkflowSummary {
-            workflow_id: "tenant_switching_456".to_string(),
-            workflow_type: "tenant_switching".to_string(),
-            status: WorkflowExecutionStatus::Running,
-            started_at: Utc::now() - chrono::Duration::minutes(30),
-            updated_at: Utc::now() - chrono::Duration::minutes(5),
-            tenant_id: tenant_context.tenant_id.clone(),
-            user_id: tenant_context.user_id.clone(),
-        },
-    ];
+    let response = search.list(&tenant_context.tenant_id, params).await?;
     
-    Ok(Json(ListWorkflowsResponse {
-        workflows,
-        total_count: 2,
-        page: params.page.unwrap_or(1),
-        page_size: params.page_size.unwrap_or(50),
-        has_more: false,
-    }))
+    Ok(Json(response))
+}
+
+/// Running workflows across tenants, or of the `tenant_id` given
+pub async fn get_in_flight_workflows(
+    Extension(search): Extension<Arc<WorkflowSearch>>,
+    Query(params): Query<InFlightParams>,
+) -> WorkflowServiceResult<Json<InFlightWorkflows>> {
+    let in_flight = search.in_flight(params).await?;
+    
+    Ok(Json(in_flight))
 }
 
 pub async fn get_workflow_history(
@@ -403,6 +573,112 @@ pub async fn get_workflow_debug_info(
     Ok(Json(debug_info))
 }
 
+pub async fn get_workflow_execution_graph(
+    Extension(history): Extension<Arc<ExecutionHistoryManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(workflow_id): Path<String>,
+    Query(params): Query<ExecutionGraphParams>,
+) -> WorkflowServiceResult<Json<ExecutionGraph>> {
+    info!("Building execution graph for workflow: {}", workflow_id);
+    
+    let graph = history
+        .graph(&tenant_context.tenant_id, &workflow_id, params.run_id.as_deref())
+        .await?;
+    
+    Ok(Json(graph))
+}
+
+pub async fn export_workflow_execution_history(
+    Extension(history): Extension<Arc<ExecutionHistoryManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(workflow_id): Path<String>,
+    Query(params): Query<ExecutionGraphParams>,
+) -> WorkflowServiceResult<([(header::HeaderName, String); 1], Json<ExecutionHistoryExport>)> {
+    info!("Exporting execution history for workflow: {}", workflow_id);
+    
+    let export = history
+        .export(&tenant_context.tenant_id, &workflow_id, params.run_id.as_deref())
+        .await?;
+    let filename = match &export.graph.run_id {
+        Some(run_id) => format!("{}-{}-history.json", workflow_id, run_id),
+        None => format!("{}-history.json", workflow_id),
+    };
+    
+    Ok((
+        [(header::CONTENT_DISPOSITION, format!("attachment; filename=\"{}\"", filename))],
+        Json(export),
+    ))
+}
+
+pub async fn get_workflow_interactions(
+    Extension(interactions): Extension<Arc<WorkflowInteractionManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(workflow_id): Path<String>,
+    Query(params): Query<InteractionParams>,
+) -> WorkflowServiceResult<Json<WorkflowInteractionsResponse>> {
+    info!("Listing signals and queries for workflow: {}", workflow_id);
+    
+    let response = interactions
+        .interactions(&caller(&tenant_context), &workflow_id, params.run_id.as_deref())
+        .await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn signal_workflow(
+    Extension(interactions): Extension<Arc<WorkflowInteractionManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path((workflow_id, signal_name)): Path<(String, String)>,
+    Query(params): Query<InteractionParams>,
+    body: Bytes,
+) -> WorkflowServiceResult<Json<SignalWorkflowResponse>> {
+    info!("Signalling workflow {} with '{}'", workflow_id, signal_name);
+    
+    let payload = interaction_payload(&body)?;
+    let response = interactions
+        .signal(&caller(&tenant_context), &workflow_id, params.run_id.as_deref(), &signal_name, &payload)
+        .await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn query_workflow(
+    Extension(interactions): Extension<Arc<WorkflowInteractionManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path((workflow_id, query_name)): Path<(String, String)>,
+    Query(params): Query<InteractionParams>,
+    body: Bytes,
+) -> WorkflowServiceResult<Json<QueryWorkflowResponse>> {
+    info!("Querying workflow {} with '{}'", workflow_id, query_name);
+    
+    let payload = interaction_payload(&body)?;
+    let response = interactions
+        .query(&caller(&tenant_context), &workflow_id, params.run_id.as_deref(), &query_name, &payload)
+        .await?;
+    
+    Ok(Json(response))
+}
+
+fn caller(tenant_context: &TenantContext) -> Caller<'_> {
+    Caller {
+        tenant_id: &tenant_context.tenant_id,
+        user_id: tenant_context.user_id.as_deref(),
+        roles: &tenant_context.roles,
+    }
+}
+
+/// Signal and query payloads are JSON objects; an empty body means no arguments
+fn interaction_payload(body: &[u8]) -> WorkflowServiceResult<serde_json::Map<String, serde_json::Value>> {
+    if body.iter().all(u8::is_ascii_whitespace) {
+        return Ok(serde_json::Map::new());
+    }
+    
+    match serde_json::from_slice(body)? {
+        serde_json::Value::Object(payload) => Ok(payload),
+        _ => Err(WorkflowServiceError::Validation("Payload must be a JSON object".to_string())),
+    }
+}
+
 // Enhanced workflow management handlers
 
 pub async fn cancel_workflow_enhanced(
@@ -430,31 +706,49 @@ pub async fn retry_workflow_enhanced(
 }
 
 pub async fn pause_workflow(
-    Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(controls): Extension<Arc<WorkflowControlManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
     Path(workflow_id): Path<String>,
-    Json(request): Json<HashMap<String, String>>,
-) -> WorkflowServiceResult<Json<crate::management::PauseWorkflowResponse>> {
+    request: Option<Json<WorkflowControlRequest>>,
+) -> WorkflowServiceResult<Json<WorkflowControlResponse>> {
     info!("Pausing workflow: {}", workflow_id);
     
-    let manager = WorkflowManager::new(config);
-    let reason = request.get("reason").cloned();
-    let response = manager.pause_workflow(&workflow_id, reason).await?;
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+    let response = controls
+        .control(&caller(&tenant_context), &workflow_id, ControlAction::Pause, request)
+        .await?;
     
     Ok(Json(response))
 }
 
 pub async fn resume_workflow(
-    Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(controls): Extension<Arc<WorkflowControlManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
     Path(workflow_id): Path<String>,
-) -> WorkflowServiceResult<Json<crate::management::ResumeWorkflowResponse>> {
+    request: Option<Json<WorkflowControlRequest>>,
+) -> WorkflowServiceResult<Json<WorkflowControlResponse>> {
     info!("Resuming workflow: {}", workflow_id);
     
-    let manager = WorkflowManager::new(config);
-    let response = manager.resume_workflow(&workflow_id).await?;
+    let request = request.map(|Json(request)| request).unwrap_or_default();
+    let response = controls
+        .control(&caller(&tenant_context), &workflow_id, ControlAction::Resume, request)
+        .await?;
     
     Ok(Json(response))
 }
 
+pub async fn get_workflow_controls(
+    Extension(controls): Extension<Arc<WorkflowControlManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(workflow_id): Path<String>,
+) -> WorkflowServiceResult<Json<WorkflowControlHistory>> {
+    info!("Getting control history for workflow: {}", workflow_id);
+    
+    let history = controls.history(&tenant_context.tenant_id, &workflow_id).await?;
+    
+    Ok(Json(history))
+}
+
 pub async fn terminate_workflow(
     Extension(config): Extension<Arc<WorkflowServiceConfig>>,
     Json(request): Json<TerminateWorkflowRequest>,
@@ -494,85 +788,90 @@ pub async fn bulk_workflow_operation(
 // Workflow versioning handlers
 
 pub async fn register_workflow_version(
-    Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(versions): Extension<Arc<WorkflowVersionManager>>,
     Json(request): Json<RegisterVersionRequest>,
 ) -> WorkflowServiceResult<Json<crate::versioning::RegisterVersionResponse>> {
     info!("Registering workflow version: {} v{}", request.workflow_type, request.version);
     
-    let version_manager = WorkflowVersionManager::new(config);
-    let response = version_manager.register_workflow_version(request).await?;
+    let response = versions.register_workflow_version(request).await?;
     
     Ok(Json(response))
 }
 
 pub async fn get_workflow_versions(
-    Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(versions): Extension<Arc<WorkflowVersionManager>>,
     Path(workflow_type): Path<String>,
 ) -> WorkflowServiceResult<Json<crate::versioning::WorkflowVersionsResponse>> {
     info!("Getting versions for workflow type: {}", workflow_type);
     
-    let version_manager = WorkflowVersionManager::new(config);
-    let response = version_manager.get_workflow_versions(&workflow_type).await?;
+    let response = versions.get_workflow_versions(&workflow_type).await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn get_outdated_workflow_executions(
+    Extension(versions): Extension<Arc<WorkflowVersionManager>>,
+    Path(workflow_type): Path<String>,
+    Query(params): Query<OutdatedExecutionsParams>,
+) -> WorkflowServiceResult<Json<crate::versioning::OutdatedExecutionsResponse>> {
+    info!("Finding executions on outdated versions of workflow type: {}", workflow_type);
+    
+    let response = versions.find_outdated_executions(&workflow_type, params).await?;
     
     Ok(Json(response))
 }
 
 pub async fn migrate_workflows(
-    Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(versions): Extension<Arc<WorkflowVersionManager>>,
     Json(request): Json<MigrateWorkflowsRequest>,
 ) -> WorkflowServiceResult<Json<crate::versioning::MigrateWorkflowsResponse>> {
     info!("Migrating workflows from {} v{} to v{}", request.workflow_type, request.from_version, request.to_version);
     
-    let version_manager = WorkflowVersionManager::new(config);
-    let response = version_manager.migrate_workflows(request).await?;
+    let response = versions.migrate_workflows(request).await?;
     
     Ok(Json(response))
 }
 
 pub async fn get_migration_status(
-    Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(versions): Extension<Arc<WorkflowVersionManager>>,
     Path(migration_id): Path<String>,
 ) -> WorkflowServiceResult<Json<crate::versioning::MigrationStatusResponse>> {
     info!("Getting migration status for: {}", migration_id);
     
-    let version_manager = WorkflowVersionManager::new(config);
-    let response = version_manager.get_migration_status(&migration_id).await?;
+    let response = versions.get_migration_status(&migration_id).await?;
     
     Ok(Json(response))
 }
 
 pub async fn rollback_migration(
-    Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(versions): Extension<Arc<WorkflowVersionManager>>,
     Json(request): Json<RollbackMigrationRequest>,
 ) -> WorkflowServiceResult<Json<crate::versioning::RollbackMigrationResponse>> {
     warn!("Rolling back migration: {}", request.migration_id);
     
-    let version_manager = WorkflowVersionManager::new(config);
-    let response = version_manager.rollback_migration(request).await?;
+    let response = versions.rollback_migration(request).await?;
     
     Ok(Json(response))
 }
 
 pub async fn deprecate_version(
-    Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(versions): Extension<Arc<WorkflowVersionManager>>,
     Json(request): Json<DeprecateVersionRequest>,
 ) -> WorkflowServiceResult<Json<crate::versioning::DeprecateVersionResponse>> {
     info!("Deprecating workflow version: {} v{}", request.workflow_type, request.version);
     
-    let version_manager = WorkflowVersionManager::new(config);
-    let response = version_manager.deprecate_version(request).await?;
+    let response = versions.deprecate_version(request).await?;
     
     Ok(Json(response))
 }
 
 pub async fn get_compatibility_matrix(
-    Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(versions): Extension<Arc<WorkflowVersionManager>>,
     Path(workflow_type): Path<String>,
 ) -> WorkflowServiceResult<Json<crate::versioning::CompatibilityMatrixResponse>> {
     info!("Getting compatibility matrix for workflow type: {}", workflow_type);
     
-    let version_manager = WorkflowVersionManager::new(config);
-    let response = version_manager.get_compatibility_matrix(&workflow_type).await?;
+    let response = versions.get_compatibility_matrix(&workflow_type).await?;
     
     Ok(Json(response))
 }
@@ -580,88 +879,118 @@ pub async fn get_compatibility_matrix(
 // Workflow template handlers
 
 pub async fn create_workflow_template(
-    Extension(config): Extension<Arc<WorkflowServiceConfig>>,
-    Json(request): Json<CreateTemplateRequest>,
-) -> WorkflowServiceResult<Json<crate::templates::CreateTemplateResponse>> {
-    info!("Creating workflow template: {}", request.template_name);
+    Extension(templates): Extension<Arc<WorkflowTemplateManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> WorkflowServiceResult<(StatusCode, Json<crate::templates::CreateTemplateResponse>)> {
+    let document = parse_document(&body, template_format(&headers))?;
+    info!("Creating workflow template '{}' for tenant: {}", document.name, tenant_context.tenant_id);
     
-    let template_manager = WorkflowTemplateManager::new(config);
-    let response = template_manager.create_template(request).await?;
+    let response = templates
+        .create_template(&tenant_context.tenant_id, tenant_context.user_id.as_deref(), document)
+        .await?;
     
-    Ok(Json(response))
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+pub async fn validate_workflow_template(
+    Extension(templates): Extension<Arc<WorkflowTemplateManager>>,
+    headers: HeaderMap,
+    body: Bytes,
+) -> WorkflowServiceResult<Json<crate::templates::ValidateTemplateResponse>> {
+    let document = parse_document(&body, template_format(&headers))?;
+    info!("Validating workflow template '{}'", document.name);
+    
+    Ok(Json(templates.validate_template(&document)))
 }
 
 pub async fn get_workflow_templates(
-    Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(templates): Extension<Arc<WorkflowTemplateManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
     Query(params): Query<GetTemplatesParams>,
 ) -> WorkflowServiceResult<Json<crate::templates::GetTemplatesResponse>> {
-    info!("Getting workflow templates");
+    info!("Getting workflow templates for tenant: {}", tenant_context.tenant_id);
     
-    let template_manager = WorkflowTemplateManager::new(config);
-    let response = template_manager.get_templates(params).await?;
+    let response = templates.get_templates(&tenant_context.tenant_id, params).await?;
     
     Ok(Json(response))
 }
 
 pub async fn get_workflow_template(
-    Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(templates): Extension<Arc<WorkflowTemplateManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
     Path(template_id): Path<String>,
 ) -> WorkflowServiceResult<Json<crate::templates::WorkflowTemplate>> {
     info!("Getting workflow template: {}", template_id);
     
-    let template_manager = WorkflowTemplateManager::new(config);
-    let response = template_manager.get_template(&template_id).await?;
+    let response = templates.get_template(&tenant_context.tenant_id, &template_id).await?;
     
     Ok(Json(response))
 }
 
-pub async fn create_workflow_from_template(
-    Extension(config): Extension<Arc<WorkflowServiceConfig>>,
-    Json(request): Json<CreateFromTemplateRequest>,
-) -> WorkflowServiceResult<Json<crate::templates::CreateFromTemplateResponse>> {
-    info!("Creating workflow from template: {}", request.template_id);
+pub async fn instantiate_workflow_template(
+    Extension(templates): Extension<Arc<WorkflowTemplateManager>>,
+    Extension(costs): Extension<Arc<CostTracker>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(template_id): Path<String>,
+    Json(request): Json<InstantiateTemplateRequest>,
+) -> WorkflowServiceResult<(StatusCode, Json<crate::templates::InstantiateTemplateResponse>)> {
+    info!("Instantiating workflow template {} for tenant: {}", template_id, tenant_context.tenant_id);
     
-    let template_manager = WorkflowTemplateManager::new(config);
-    let response = template_manager.create_workflow_from_template(request).await?;
+    costs.check_start(&tenant_context.tenant_id, TEMPLATE_WORKFLOW_TYPE).await?;
+    let response = templates
+        .instantiate_template(&tenant_context.tenant_id, tenant_context.user_id.as_deref(), &template_id, request)
+        .await?;
     
-    Ok(Json(response))
+    Ok((StatusCode::ACCEPTED, Json(response)))
 }
 
 pub async fn update_workflow_template(
-    Extension(config): Extension<Arc<WorkflowServiceConfig>>,
-    Json(request): Json<UpdateTemplateRequest>,
+    Extension(templates): Extension<Arc<WorkflowTemplateManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(template_id): Path<String>,
+    headers: HeaderMap,
+    body: Bytes,
 ) -> WorkflowServiceResult<Json<crate::templates::UpdateTemplateResponse>> {
-    info!("Updating workflow template: {}", request.template_id);
+    info!("Updating workflow template: {}", template_id);
     
-    let template_manager = WorkflowTemplateManager::new(config);
-    let response = template_manager.update_template(request).await?;
+    let document = parse_document(&body, template_format(&headers))?;
+    let response = templates
+        .update_template(&tenant_context.tenant_id, &template_id, document)
+        .await?;
     
     Ok(Json(response))
 }
 
 pub async fn delete_workflow_template(
-    Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(templates): Extension<Arc<WorkflowTemplateManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
     Path(template_id): Path<String>,
     Query(params): Query<HashMap<String, String>>,
 ) -> WorkflowServiceResult<Json<crate::templates::DeleteTemplateResponse>> {
     info!("Deleting workflow template: {}", template_id);
     
-    let template_manager = WorkflowTemplateManager::new(config);
     let force = params.get("force").map(|s| s == "true").unwrap_or(false);
-    let response = template_manager.delete_template(&template_id, force).await?;
+    let response = templates
+        .delete_template(&tenant_context.tenant_id, &template_id, force)
+        .await?;
     
     Ok(Json(response))
 }
 
+/// Template documents may be sent as JSON or, with a YAML content type, as YAML
+fn template_format(headers: &HeaderMap) -> DocumentFormat {
+    DocumentFormat::from_content_type(headers.get(header::CONTENT_TYPE).and_then(|value| value.to_str().ok()))
+}
+
 pub async fn analyze_workflow_patterns(
-    Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(templates): Extension<Arc<WorkflowTemplateManager>>,
     Extension(tenant_context): Extension<TenantContext>,
     Query(params): Query<HashMap<String, String>>,
 ) -> WorkflowServiceResult<Json<crate::templates::PatternAnalysisResponse>> {
     info!("Analyzing workflow patterns for tenant: {}", tenant_context.tenant_id);
     
-    let template_manager = WorkflowTemplateManager::new(config);
-    
     let analysis_params = PatternAnalysisParams {
         tenant_id: Some(tenant_context.tenant_id),
         workflow_types: params.get("workflow_types").map(|s| s.split(',').map(|t| t.to_string()).collect()),
@@ -669,31 +998,630 @@ pub async fn analyze_workflow_patterns(
         min_occurrences: params.get("min_occurrences").and_then(|s| s.parse().ok()),
     };
     
-    let response = template_manager.analyze_workflow_patterns(analysis_params).await?;
+    let response = templates.analyze_workflow_patterns(analysis_params).await?;
     
     Ok(Json(response))
 }
 
 pub async fn generate_template_from_workflows(
-    Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(templates): Extension<Arc<WorkflowTemplateManager>>,
     Json(request): Json<GenerateTemplateRequest>,
 ) -> WorkflowServiceResult<Json<crate::templates::GenerateTemplateResponse>> {
     info!("Generating template from {} workflows", request.workflow_ids.len());
     
-    let template_manager = WorkflowTemplateManager::new(config);
-    let response = template_manager.generate_template_from_workflows(request).await?;
+    let response = templates.generate_template_from_workflows(request).await?;
     
     Ok(Json(response))
 }
 
 pub async fn get_template_usage(
-    Extension(config): Extension<Arc<WorkflowServiceConfig>>,
+    Extension(templates): Extension<Arc<WorkflowTemplateManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
     Path(template_id): Path<String>,
 ) -> WorkflowServiceResult<Json<crate::templates::TemplateUsageResponse>> {
     info!("Getting usage statistics for template: {}", template_id);
     
-    let template_manager = WorkflowTemplateManager::new(config);
-    let response = template_manager.get_template_usage(&template_id).await?;
+    let response = templates.get_template_usage(&tenant_context.tenant_id, &template_id).await?;
+    
+    Ok(Json(response))
+}
+
+// Workflow schedule handlers
+
+pub async fn create_workflow_schedule(
+    Extension(schedules): Extension<Arc<WorkflowScheduleManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(request): Json<CreateScheduleRequest>,
+) -> WorkflowServiceResult<(StatusCode, Json<crate::schedules::ScheduleResponse>)> {
+    info!("Creating workflow schedule '{}' for tenant: {}", request.name, tenant_context.tenant_id);
+    
+    let response = schedules
+        .create_schedule(&tenant_context.tenant_id, tenant_context.user_id.as_deref(), request)
+        .await?;
+    
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+pub async fn list_workflow_schedules(
+    Extension(schedules): Extension<Arc<WorkflowScheduleManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> WorkflowServiceResult<Json<crate::schedules::ListSchedulesResponse>> {
+    let response = schedules.list_schedules(&tenant_context.tenant_id).await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn get_workflow_schedule(
+    Extension(schedules): Extension<Arc<WorkflowScheduleManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(schedule_id): Path<String>,
+) -> WorkflowServiceResult<Json<crate::schedules::ScheduleResponse>> {
+    let response = schedules.get_schedule(&tenant_context.tenant_id, &schedule_id).await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn update_workflow_schedule(
+    Extension(schedules): Extension<Arc<WorkflowScheduleManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(schedule_id): Path<String>,
+    Json(request): Json<UpdateScheduleRequest>,
+) -> WorkflowServiceResult<Json<crate::schedules::ScheduleResponse>> {
+    let response = schedules
+        .update_schedule(&tenant_context.tenant_id, &schedule_id, request)
+        .await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn delete_workflow_schedule(
+    Extension(schedules): Extension<Arc<WorkflowScheduleManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(schedule_id): Path<String>,
+) -> WorkflowServiceResult<StatusCode> {
+    schedules.delete_schedule(&tenant_context.tenant_id, &schedule_id).await?;
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn pause_workflow_schedule(
+    Extension(schedules): Extension<Arc<WorkflowScheduleManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(schedule_id): Path<String>,
+    request: Option<Json<PauseScheduleRequest>>,
+) -> WorkflowServiceResult<Json<crate::schedules::ScheduleResponse>> {
+    info!("Pausing workflow schedule: {}", schedule_id);
+    
+    let reason = request.and_then(|Json(r)| r.reason);
+    let response = schedules
+        .pause_schedule(&tenant_context.tenant_id, &schedule_id, reason)
+        .await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn resume_workflow_schedule(
+    Extension(schedules): Extension<Arc<WorkflowScheduleManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(schedule_id): Path<String>,
+) -> WorkflowServiceResult<Json<crate::schedules::ScheduleResponse>> {
+    info!("Resuming workflow schedule: {}", schedule_id);
+    
+    let response = schedules.resume_schedule(&tenant_context.tenant_id, &schedule_id).await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn get_workflow_schedule_limits(
+    Extension(schedules): Extension<Arc<WorkflowScheduleManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> WorkflowServiceResult<Json<crate::schedules::ScheduleLimits>> {
+    let response = schedules.get_limits(&tenant_context.tenant_id).await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn set_workflow_schedule_limits(
+    Extension(schedules): Extension<Arc<WorkflowScheduleManager>>,
+    Path(tenant_id): Path<String>,
+    Json(request): Json<SetScheduleLimitsRequest>,
+) -> WorkflowServiceResult<Json<crate::schedules::ScheduleLimits>> {
+    let response = schedules.set_limits(&tenant_id, request).await?;
+    
+    Ok(Json(response))
+}
+
+// Task queue routing handlers
+
+pub async fn get_workflow_task_queues(
+    Extension(task_queues): Extension<Arc<TaskQueueRouter>>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> WorkflowServiceResult<Json<TaskQueueRoutingResponse>> {
+    let response = task_queues.get_routing(&tenant_context.tenant_id).await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn set_workflow_task_queues(
+    Extension(task_queues): Extension<Arc<TaskQueueRouter>>,
+    Path(tenant_id): Path<String>,
+    Json(request): Json<SetTaskQueueAssignmentRequest>,
+) -> WorkflowServiceResult<Json<TaskQueueRoutingResponse>> {
+    let response = task_queues.set_assignment(&tenant_id, request).await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn clear_workflow_task_queues(
+    Extension(task_queues): Extension<Arc<TaskQueueRouter>>,
+    Path(tenant_id): Path<String>,
+) -> WorkflowServiceResult<Json<TaskQueueRoutingResponse>> {
+    let response = task_queues.clear_assignment(&tenant_id).await?;
+    
+    Ok(Json(response))
+}
+
+// Workflow cost and budget handlers
+
+pub async fn get_workflow_cost_report(
+    Extension(costs): Extension<Arc<CostTracker>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Query(params): Query<CostReportParams>,
+) -> WorkflowServiceResult<Json<CostReport>> {
+    info!("Getting workflow cost report for tenant: {}", tenant_context.tenant_id);
+    
+    let report = costs.report(&tenant_context.tenant_id, params).await?;
+    
+    Ok(Json(report))
+}
+
+pub async fn get_workflow_run_cost(
+    Extension(costs): Extension<Arc<CostTracker>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(workflow_id): Path<String>,
+) -> WorkflowServiceResult<Json<RunCost>> {
+    let cost = costs.get_run_cost(&tenant_context.tenant_id, &workflow_id).await?;
+    
+    Ok(Json(cost))
+}
+
+pub async fn get_workflow_budget(
+    Extension(costs): Extension<Arc<CostTracker>>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> WorkflowServiceResult<Json<BudgetStatus>> {
+    let status = costs.budget_status(&tenant_context.tenant_id).await?;
+    
+    Ok(Json(status))
+}
+
+pub async fn set_workflow_budget(
+    Extension(costs): Extension<Arc<CostTracker>>,
+    Path(tenant_id): Path<String>,
+    Json(request): Json<SetWorkflowBudgetRequest>,
+) -> WorkflowServiceResult<Json<BudgetStatus>> {
+    let status = costs.set_budget(&tenant_id, request).await?;
+    
+    Ok(Json(status))
+}
+
+// Workflow SLA handlers
+
+pub async fn list_workflow_slas(
+    Extension(slas): Extension<Arc<SlaMonitor>>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> WorkflowServiceResult<Json<Vec<WorkflowSla>>> {
+    let slas = slas.list_slas(&tenant_context.tenant_id).await?;
+    
+    Ok(Json(slas))
+}
+
+pub async fn get_workflow_sla(
+    Extension(slas): Extension<Arc<SlaMonitor>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(workflow_type): Path<String>,
+) -> WorkflowServiceResult<Json<WorkflowSla>> {
+    let sla = slas.get_sla(&tenant_context.tenant_id, &workflow_type).await?;
+    
+    Ok(Json(sla))
+}
+
+pub async fn set_workflow_sla(
+    Extension(slas): Extension<Arc<SlaMonitor>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(workflow_type): Path<String>,
+    Json(request): Json<SetWorkflowSlaRequest>,
+) -> WorkflowServiceResult<Json<WorkflowSla>> {
+    info!("Setting SLA for {} in tenant: {}", workflow_type, tenant_context.tenant_id);
+    
+    let sla = slas
+        .set_sla(&tenant_context.tenant_id, tenant_context.user_id.as_deref(), &workflow_type, request)
+        .await?;
+    
+    Ok(Json(sla))
+}
+
+pub async fn delete_workflow_sla(
+    Extension(slas): Extension<Arc<SlaMonitor>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(workflow_type): Path<String>,
+) -> WorkflowServiceResult<StatusCode> {
+    slas.delete_sla(&tenant_context.tenant_id, &workflow_type).await?;
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn get_workflow_sla_status(
+    Extension(slas): Extension<Arc<SlaMonitor>>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> WorkflowServiceResult<Json<SlaStatusResponse>> {
+    info!("Evaluating workflow SLAs for tenant: {}", tenant_context.tenant_id);
+    
+    let status = slas.status(&tenant_context.tenant_id).await?;
+    
+    Ok(Json(status))
+}
+
+pub async fn list_workflow_sla_breaches(
+    Extension(slas): Extension<Arc<SlaMonitor>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Query(params): Query<SlaBreachParams>,
+) -> WorkflowServiceResult<Json<SlaBreachListResponse>> {
+    let breaches = slas.list_breaches(&tenant_context.tenant_id, params).await?;
+    
+    Ok(Json(breaches))
+}
+
+// Batch launcher handlers
+
+pub async fn submit_workflow_batch(
+    Extension(batches): Extension<Arc<WorkflowBatchManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(request): Json<SubmitBatchRequest>,
+) -> WorkflowServiceResult<(StatusCode, Json<WorkflowApiResponse<serde_json::Value>>)> {
+    info!(
+        "Submitting batch of {} {} runs for tenant: {}",
+        request.inputs.len(), request.workflow_type, tenant_context.tenant_id
+    );
+    
+    let response = batches
+        .submit(&tenant_context.tenant_id, tenant_context.user_id.as_deref(), request)
+        .await?;
+    
+    Ok((StatusCode::ACCEPTED, Json(response)))
+}
+
+pub async fn get_workflow_batch_status(
+    Extension(batches): Extension<Arc<WorkflowBatchManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(batch_id): Path<String>,
+) -> WorkflowServiceResult<Json<OperationStatusResponse>> {
+    let status = batches.status(&tenant_context.tenant_id, &batch_id).await?;
+    
+    Ok(Json(status))
+}
+
+pub async fn list_workflow_batch_children(
+    Extension(batches): Extension<Arc<WorkflowBatchManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(batch_id): Path<String>,
+    Query(params): Query<BatchChildrenParams>,
+) -> WorkflowServiceResult<Json<BatchChildrenResponse>> {
+    let response = batches.list_children(&tenant_context.tenant_id, &batch_id, params).await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn cancel_workflow_batch(
+    Extension(batches): Extension<Arc<WorkflowBatchManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(batch_id): Path<String>,
+) -> WorkflowServiceResult<Json<WorkflowBatch>> {
+    info!("Cancelling batch {} for tenant: {}", batch_id, tenant_context.tenant_id);
+    
+    let batch = batches
+        .cancel(&tenant_context.tenant_id, tenant_context.user_id.as_deref(), &batch_id)
+        .await?;
+    
+    Ok(Json(batch))
+}
+
+// Workflow calendar handlers
+
+pub async fn create_workflow_calendar(
+    Extension(schedules): Extension<Arc<WorkflowScheduleManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(request): Json<CreateCalendarRequest>,
+) -> WorkflowServiceResult<(StatusCode, Json<crate::schedules::WorkflowCalendar>)> {
+    let response = schedules.create_calendar(&tenant_context.tenant_id, request).await?;
+    
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+pub async fn list_workflow_calendars(
+    Extension(schedules): Extension<Arc<WorkflowScheduleManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> WorkflowServiceResult<Json<Vec<crate::schedules::WorkflowCalendar>>> {
+    let response = schedules.list_calendars(&tenant_context.tenant_id).await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn update_workflow_calendar(
+    Extension(schedules): Extension<Arc<WorkflowScheduleManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(calendar_id): Path<String>,
+    Json(request): Json<UpdateCalendarRequest>,
+) -> WorkflowServiceResult<Json<crate::schedules::WorkflowCalendar>> {
+    let response = schedules
+        .update_calendar(&tenant_context.tenant_id, &calendar_id, request)
+        .await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn delete_workflow_calendar(
+    Extension(schedules): Extension<Arc<WorkflowScheduleManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(calendar_id): Path<String>,
+) -> WorkflowServiceResult<StatusCode> {
+    schedules.delete_calendar(&tenant_context.tenant_id, &calendar_id).await?;
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+// Approval inbox handlers
+
+pub async fn list_approvals(
+    Extension(approvals): Extension<Arc<ApprovalManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Query(params): Query<ApprovalInboxParams>,
+) -> WorkflowServiceResult<Json<ApprovalInboxResponse>> {
+    let response = approvals
+        .inbox(&tenant_context.tenant_id, tenant_context.user_id.as_deref(), &tenant_context.roles, params)
+        .await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn get_approval(
+    Extension(approvals): Extension<Arc<ApprovalManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(approval_id): Path<String>,
+) -> WorkflowServiceResult<Json<ApprovalTask>> {
+    let response = approvals
+        .get_approval(&tenant_context.tenant_id, tenant_context.user_id.as_deref(), &tenant_context.roles, &approval_id)
+        .await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn approve_approval(
+    Extension(approvals): Extension<Arc<ApprovalManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(approval_id): Path<String>,
+    request: Option<Json<DecideApprovalRequest>>,
+) -> WorkflowServiceResult<Json<ApprovalTask>> {
+    decide_approval(approvals, tenant_context, approval_id, ApprovalDecision::Approve, request).await
+}
+
+pub async fn reject_approval(
+    Extension(approvals): Extension<Arc<ApprovalManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(approval_id): Path<String>,
+    request: Option<Json<DecideApprovalRequest>>,
+) -> WorkflowServiceResult<Json<ApprovalTask>> {
+    decide_approval(approvals, tenant_context, approval_id, ApprovalDecision::Reject, request).await
+}
+
+async fn decide_approval(
+    approvals: Arc<ApprovalManager>,
+    tenant_context: TenantContext,
+    approval_id: String,
+    decision: ApprovalDecision,
+    request: Option<Json<DecideApprovalRequest>>,
+) -> WorkflowServiceResult<Json<ApprovalTask>> {
+    info!("Recording {:?} decision on approval {} for tenant: {}", decision, approval_id, tenant_context.tenant_id);
+    
+    let request = request.map(|Json(r)| r).unwrap_or(DecideApprovalRequest { comment: None });
+    let response = approvals
+        .decide(
+            &tenant_context.tenant_id,
+            tenant_context.user_id.as_deref(),
+            &tenant_context.roles,
+            &approval_id,
+            decision,
+            request,
+        )
+        .await?;
+    
+    Ok(Json(response))
+}
+
+// Dead-letter queue handlers
+
+pub async fn list_dead_letters(
+    Extension(dead_letters): Extension<Arc<DeadLetterQueue>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Query(params): Query<DeadLetterListParams>,
+) -> WorkflowServiceResult<Json<DeadLetterListResponse>> {
+    let response = dead_letters.list(&tenant_context.tenant_id, params).await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn get_dead_letter(
+    Extension(dead_letters): Extension<Arc<DeadLetterQueue>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(dead_letter_id): Path<String>,
+) -> WorkflowServiceResult<Json<DeadLetter>> {
+    let response = dead_letters.get(&tenant_context.tenant_id, &dead_letter_id).await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn update_dead_letter_input(
+    Extension(dead_letters): Extension<Arc<DeadLetterQueue>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(dead_letter_id): Path<String>,
+    Json(request): Json<UpdateDeadLetterInputRequest>,
+) -> WorkflowServiceResult<Json<DeadLetter>> {
+    info!("Editing input of dead letter {} for tenant: {}", dead_letter_id, tenant_context.tenant_id);
+    
+    let response = dead_letters
+        .update_input(&tenant_context.tenant_id, tenant_context.user_id.as_deref(), &dead_letter_id, request)
+        .await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn resubmit_dead_letter(
+    Extension(dead_letters): Extension<Arc<DeadLetterQueue>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(dead_letter_id): Path<String>,
+) -> WorkflowServiceResult<(StatusCode, Json<DeadLetter>)> {
+    info!("Resubmitting dead letter {} for tenant: {}", dead_letter_id, tenant_context.tenant_id);
+    
+    let response = dead_letters
+        .resubmit(&tenant_context.tenant_id, tenant_context.user_id.as_deref(), &dead_letter_id)
+        .await?;
+    
+    Ok((StatusCode::ACCEPTED, Json(response)))
+}
+
+pub async fn bulk_resubmit_dead_letters(
+    Extension(dead_letters): Extension<Arc<DeadLetterQueue>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(request): Json<BulkResubmitRequest>,
+) -> WorkflowServiceResult<(StatusCode, Json<BulkResubmitResponse>)> {
+    info!("Bulk resubmitting dead letters for tenant: {}", tenant_context.tenant_id);
+    
+    let response = dead_letters
+        .bulk_resubmit(&tenant_context.tenant_id, tenant_context.user_id.as_deref(), request)
+        .await?;
+    
+    Ok((StatusCode::ACCEPTED, Json(response)))
+}
+
+pub async fn discard_dead_letter(
+    Extension(dead_letters): Extension<Arc<DeadLetterQueue>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(dead_letter_id): Path<String>,
+    request: Option<Json<DiscardDeadLetterRequest>>,
+) -> WorkflowServiceResult<Json<DeadLetter>> {
+    info!("Discarding dead letter {} for tenant: {}", dead_letter_id, tenant_context.tenant_id);
+    
+    let request = request.map(|Json(r)| r).unwrap_or_default();
+    let response = dead_letters
+        .discard(&tenant_context.tenant_id, tenant_context.user_id.as_deref(), &dead_letter_id, request)
+        .await?;
+    
+    Ok(Json(response))
+}
+
+// Workflow trigger handlers
+
+pub async fn list_workflow_triggers(
+    Extension(triggers): Extension<Arc<WorkflowTriggerManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+) -> WorkflowServiceResult<Json<ListTriggersResponse>> {
+    let response = triggers.list_triggers(&tenant_context.tenant_id).await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn create_workflow_trigger(
+    Extension(triggers): Extension<Arc<WorkflowTriggerManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(request): Json<CreateTriggerRequest>,
+) -> WorkflowServiceResult<(StatusCode, Json<WorkflowTrigger>)> {
+    info!("Creating workflow trigger '{}' for tenant: {}", request.name, tenant_context.tenant_id);
+    
+    let response = triggers
+        .create_trigger(&tenant_context.tenant_id, tenant_context.user_id.as_deref(), request)
+        .await?;
+    
+    Ok((StatusCode::CREATED, Json(response)))
+}
+
+pub async fn get_workflow_trigger(
+    Extension(triggers): Extension<Arc<WorkflowTriggerManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(trigger_id): Path<String>,
+) -> WorkflowServiceResult<Json<WorkflowTrigger>> {
+    let response = triggers.get_trigger(&tenant_context.tenant_id, &trigger_id).await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn update_workflow_trigger(
+    Extension(triggers): Extension<Arc<WorkflowTriggerManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(trigger_id): Path<String>,
+    Json(request): Json<UpdateTriggerRequest>,
+) -> WorkflowServiceResult<Json<WorkflowTrigger>> {
+    info!("Updating workflow trigger {} for tenant: {}", trigger_id, tenant_context.tenant_id);
+    
+    let response = triggers
+        .update_trigger(&tenant_context.tenant_id, tenant_context.user_id.as_deref(), &trigger_id, request)
+        .await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn delete_workflow_trigger(
+    Extension(triggers): Extension<Arc<WorkflowTriggerManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(trigger_id): Path<String>,
+) -> WorkflowServiceResult<StatusCode> {
+    info!("Deleting workflow trigger {} for tenant: {}", trigger_id, tenant_context.tenant_id);
+    
+    triggers.delete_trigger(&tenant_context.tenant_id, &trigger_id).await?;
+    
+    Ok(StatusCode::NO_CONTENT)
+}
+
+pub async fn list_workflow_trigger_firings(
+    Extension(triggers): Extension<Arc<WorkflowTriggerManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(trigger_id): Path<String>,
+    Query(params): Query<TriggerFiringParams>,
+) -> WorkflowServiceResult<Json<TriggerFiringsResponse>> {
+    let response = triggers
+        .list_firings(&tenant_context.tenant_id, &trigger_id, params)
+        .await?;
+    
+    Ok(Json(response))
+}
+
+pub async fn publish_workflow_event(
+    Extension(triggers): Extension<Arc<WorkflowTriggerManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Json(request): Json<PublishEventRequest>,
+) -> WorkflowServiceResult<(StatusCode, Json<PublishEventResponse>)> {
+    let response = triggers.publish_event(&tenant_context.tenant_id, request).await?;
+    
+    Ok((StatusCode::ACCEPTED, Json(response)))
+}
+
+pub async fn receive_workflow_webhook(
+    Extension(triggers): Extension<Arc<WorkflowTriggerManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(source): Path<String>,
+    Json(body): Json<serde_json::Value>,
+) -> WorkflowServiceResult<(StatusCode, Json<PublishEventResponse>)> {
+    let response = triggers
+        .receive_webhook(&tenant_context.tenant_id, &source, body)
+        .await?;
+    
+    Ok((StatusCode::ACCEPTED, Json(response)))
+}
+
+pub async fn get_workflow_event(
+    Extension(triggers): Extension<Arc<WorkflowTriggerManager>>,
+    Extension(tenant_context): Extension<TenantContext>,
+    Path(event_id): Path<String>,
+) -> WorkflowServiceResult<Json<TriggerEventDetails>> {
+    let response = triggers.get_event(&tenant_context.tenant_id, &event_id).await?;
     
     Ok(Json(response))
 }
@@ -706,6 +1634,14 @@ pub struct WorkflowStartResponse {
     pub status: String,
     pub result: Option<serde_json::Value>,
     pub started_at: chrono::DateTime<Utc>,
+    /// Lane the run was scheduled in
+    pub lane: PriorityLane,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct StartWorkflowParams {
+    /// `Low` moves the run to the batch lane
+    pub priority: Option<WorkflowPriority>,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -739,14 +1675,6 @@ pub struct WorkflowProgressInfo {
     pub status_message: Option<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct WorkflowCancelResponse {
-    pub workflow_id: String,
-    pub cancelled: bool,
-    pub cancelled_at: chrono::DateTime<Utc>,
-    pub message: String,
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct WorkflowRetryResponse {
     pub workflow_id: String,
@@ -756,34 +1684,6 @@ pub struct WorkflowRetryResponse {
     pub message: String,
 }
 
-#[derive(Debug, Deserialize)]
-pub struct ListWorkflowsParams {
-    pub page: Option<u32>,
-    pub page_size: Option<u32>,
-    pub status: Option<String>,
-    pub workflow_type: Option<String>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ListWorkflowsResponse {
-    pub workflows: Vec<WorkflowSummary>,
-    pub total_count: u64,
-    pub page: u32,
-    pub page_size: u32,
-    pub has_more: bool,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct WorkflowSummary {
-    pub workflow_id: String,
-    pub workflow_type: String,
-    pub status: WorkflowExecutionStatus,
-    pub started_at: chrono::DateTime<Utc>,
-    pub updated_at: chrono::DateTime<Utc>,
-    pub tenant_id: String,
-    pub user_id: Option<String>,
-}
-
 #[derive(Debug, Deserialize)]
 pub struct WorkflowHistoryParams {
     pub workflow_id: Option<String>,
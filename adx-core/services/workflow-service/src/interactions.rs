@@ -0,0 +1,396 @@
+//! Signal and query pass-through for running workflows.
+//!
+//! Frontends send signals to and query running workflows through two
+//! generic endpoints instead of one endpoint per signal. Every workflow type
+//! declares the signals and queries it accepts, the payload each one takes
+//! and the roles allowed to use it; anything not declared is rejected, so
+//! internal signals such as approval decisions can't be sent from outside.
+
+use crate::{
+    approvals::require_user,
+    error::{WorkflowServiceError, WorkflowServiceResult},
+    templates::{bind_parameters, parameters_schema, ParameterType, TemplateParameter, TEMPLATE_WORKFLOW_TYPE},
+    visualization::{run_is_closed, started_workflow_type, tenant_history},
+};
+use adx_shared::temporal::AdxTemporalClient;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::collections::HashMap;
+use tracing::info;
+
+/// Role allowed to steer long-running operations
+pub(crate) const OPERATOR_ROLE: &str = "admin";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "snake_case")]
+pub enum InteractionKind {
+    Signal,
+    Query,
+}
+
+impl InteractionKind {
+    fn label(self) -> &'static str {
+        match self {
+            InteractionKind::Signal => "signal",
+            InteractionKind::Query => "query",
+        }
+    }
+}
+
+/// A signal or query a workflow type accepts
+#[derive(Debug, Clone)]
+pub struct InteractionSpec {
+    pub name: String,
+    pub kind: InteractionKind,
+    pub description: String,
+    pub parameters: Vec<TemplateParameter>,
+    /// Roles allowed to use it; empty means any member of the tenant
+    pub allowed_roles: Vec<String>,
+}
+
+impl InteractionSpec {
+    pub fn signal(name: &str, description: &str) -> Self {
+        Self::new(name, InteractionKind::Signal, description)
+    }
+
+    pub fn query(name: &str, description: &str) -> Self {
+        Self::new(name, InteractionKind::Query, description)
+    }
+
+    fn new(name: &str, kind: InteractionKind, description: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            kind,
+            description: description.to_string(),
+            parameters: Vec::new(),
+            allowed_roles: Vec::new(),
+        }
+    }
+
+    pub fn parameter(mut self, parameter: TemplateParameter) -> Self {
+        self.parameters.push(parameter);
+        self
+    }
+
+    pub fn allowed_roles(mut self, roles: &[&str]) -> Self {
+        self.allowed_roles = roles.iter().map(|role| role.to_string()).collect();
+        self
+    }
+
+    pub fn permits(&self, roles: &[String]) -> bool {
+        self.allowed_roles.is_empty() || self.allowed_roles.iter().any(|role| roles.contains(role))
+    }
+
+    /// Check a payload against the declared parameters
+    pub fn bind(&self, payload: &Map<String, Value>) -> WorkflowServiceResult<Map<String, Value>> {
+        let owner = format!("{} '{}'", capitalize(self.kind.label()), self.name);
+        bind_parameters(&owner, &self.parameters, payload)
+    }
+
+    fn describe(&self) -> InteractionDescription {
+        InteractionDescription {
+            name: self.name.clone(),
+            kind: self.kind,
+            description: self.description.clone(),
+            allowed_roles: self.allowed_roles.clone(),
+            payload_schema: parameters_schema(&self.parameters),
+        }
+    }
+}
+
+/// The signals and queries each workflow type accepts
+#[derive(Debug, Clone, Default)]
+pub struct InteractionRegistry {
+    specs: HashMap<String, Vec<InteractionSpec>>,
+}
+
+impl InteractionRegistry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Interactions supported by the workflows this service runs
+    pub fn builtin() -> Self {
+        let mut registry = Self::new();
+
+        for workflow_type in [
+            "user_onboarding_workflow",
+            "tenant_switching_workflow",
+            "data_migration_workflow",
+            "bulk_operation_workflow",
+            "compliance_workflow",
+            TEMPLATE_WORKFLOW_TYPE,
+        ] {
+            registry.register(
+                workflow_type,
+                InteractionSpec::query("progress", "Current step and completion percentage"),
+            );
+        }
+
+        registry.register(
+            "bulk_operation_workflow",
+            InteractionSpec::signal("adjust_batch_size", "Change the size of the remaining batches")
+                .parameter(integer_parameter("batch_size", "Items per batch", &["min:1", "max:1000"]))
+                .allowed_roles(&[OPERATOR_ROLE]),
+        );
+        registry.register(
+            "data_migration_workflow",
+            InteractionSpec::signal("throttle", "Limit how fast the remaining records are copied")
+                .parameter(integer_parameter("records_per_second", "Copy rate limit", &["min:1"]))
+                .allowed_roles(&[OPERATOR_ROLE]),
+        );
+        registry.register(
+            TEMPLATE_WORKFLOW_TYPE,
+            InteractionSpec::query("step_status", "Status of the template's steps")
+                .parameter(TemplateParameter {
+                    name: "step_id".to_string(),
+                    description: "Only report this step".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: false,
+                    default_value: None,
+                    validation_rules: vec!["non_empty".to_string()],
+                }),
+        );
+
+        registry
+    }
+
+    /// Declare a signal or query for a workflow type, replacing an earlier
+    /// declaration of the same name and kind
+    pub fn register(&mut self, workflow_type: &str, spec: InteractionSpec) {
+        let specs = self.specs.entry(workflow_type.to_string()).or_default();
+        specs.retain(|existing| existing.name != spec.name || existing.kind != spec.kind);
+        specs.push(spec);
+    }
+
+    pub fn lookup(&self, workflow_type: &str, kind: InteractionKind, name: &str) -> Option<&InteractionSpec> {
+        self.specs
+            .get(workflow_type)?
+            .iter()
+            .find(|spec| spec.kind == kind && spec.name == name)
+    }
+
+    pub fn for_workflow_type(&self, workflow_type: &str) -> &[InteractionSpec] {
+        self.specs.get(workflow_type).map(Vec::as_slice).unwrap_or_default()
+    }
+}
+
+fn integer_parameter(name: &str, description: &str, rules: &[&str]) -> TemplateParameter {
+    TemplateParameter {
+        name: name.to_string(),
+        description: description.to_string(),
+        parameter_type: ParameterType::Integer,
+        required: true,
+        default_value: None,
+        validation_rules: rules.iter().map(|rule| rule.to_string()).collect(),
+    }
+}
+
+fn capitalize(text: &str) -> String {
+    let mut chars = text.chars();
+    match chars.next() {
+        Some(first) => first.to_ascii_uppercase().to_string() + chars.as_str(),
+        None => String::new(),
+    }
+}
+
+#[derive(Debug, Deserialize, Default)]
+pub struct InteractionParams {
+    pub run_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InteractionDescription {
+    pub name: String,
+    pub kind: InteractionKind,
+    pub description: String,
+    pub allowed_roles: Vec<String>,
+    pub payload_schema: Value,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkflowInteractionsResponse {
+    pub workflow_id: String,
+    pub workflow_type: String,
+    pub running: bool,
+    /// Only what the caller's roles allow
+    pub signals: Vec<InteractionDescription>,
+    pub queries: Vec<InteractionDescription>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SignalWorkflowResponse {
+    pub workflow_id: String,
+    pub run_id: Option<String>,
+    pub signal_name: String,
+    pub payload: Map<String, Value>,
+    pub sent_by: String,
+    pub sent_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QueryWorkflowResponse {
+    pub workflow_id: String,
+    pub run_id: Option<String>,
+    pub query_name: String,
+    pub result: Value,
+}
+
+/// Caller of an interaction endpoint
+pub struct Caller<'a> {
+    pub tenant_id: &'a str,
+    pub user_id: Option<&'a str>,
+    pub roles: &'a [String],
+}
+
+/// Checks signals and queries against the registry before passing them on
+/// to Temporal
+pub struct WorkflowInteractionManager {
+    temporal: AdxTemporalClient,
+    registry: InteractionRegistry,
+}
+
+impl WorkflowInteractionManager {
+    pub fn new(temporal: AdxTemporalClient, registry: InteractionRegistry) -> Self {
+        Self { temporal, registry }
+    }
+
+    pub async fn interactions(
+        &self,
+        caller: &Caller<'_>,
+        workflow_id: &str,
+        run_id: Option<&str>,
+    ) -> WorkflowServiceResult<WorkflowInteractionsResponse> {
+        let (workflow_type, running) = self.workflow(caller, workflow_id, run_id).await?;
+        let permitted = |kind: InteractionKind| {
+            self.registry
+                .for_workflow_type(&workflow_type)
+                .iter()
+                .filter(|spec| spec.kind == kind && spec.permits(caller.roles))
+                .map(InteractionSpec::describe)
+                .collect()
+        };
+
+        Ok(WorkflowInteractionsResponse {
+            workflow_id: workflow_id.to_string(),
+            signals: permitted(InteractionKind::Signal),
+            queries: permitted(InteractionKind::Query),
+            workflow_type,
+            running,
+        })
+    }
+
+    pub async fn signal(
+        &self,
+        caller: &Caller<'_>,
+        workflow_id: &str,
+        run_id: Option<&str>,
+        signal_name: &str,
+        payload: &Map<String, Value>,
+    ) -> WorkflowServiceResult<SignalWorkflowResponse> {
+        let user_id = require_user(caller.user_id)?;
+        let (workflow_type, running) = self.workflow(caller, workflow_id, run_id).await?;
+        let spec = self.authorize(caller, &workflow_type, InteractionKind::Signal, signal_name)?;
+        let payload = spec.bind(payload)?;
+
+        if !running {
+            return Err(WorkflowServiceError::InvalidOperation(format!(
+                "Workflow {} is not running",
+                workflow_id
+            )));
+        }
+
+        info!(
+            workflow_id = workflow_id,
+            signal_name = signal_name,
+            user_id = user_id,
+            tenant_id = caller.tenant_id,
+            "Passing signal through to workflow"
+        );
+
+        self.temporal
+            .signal_workflow(workflow_id, run_id, signal_name, Value::Object(payload.clone()))
+            .await
+            .map_err(|e| WorkflowServiceError::Temporal(e.to_string()))?;
+
+        Ok(SignalWorkflowResponse {
+            workflow_id: workflow_id.to_string(),
+            run_id: run_id.map(str::to_string),
+            signal_name: signal_name.to_string(),
+            payload,
+            sent_by: user_id.to_string(),
+            sent_at: Utc::now(),
+        })
+    }
+
+    pub async fn query(
+        &self,
+        caller: &Caller<'_>,
+        workflow_id: &str,
+        run_id: Option<&str>,
+        query_name: &str,
+        payload: &Map<String, Value>,
+    ) -> WorkflowServiceResult<QueryWorkflowResponse> {
+        let (workflow_type, _) = self.workflow(caller, workflow_id, run_id).await?;
+        let spec = self.authorize(caller, &workflow_type, InteractionKind::Query, query_name)?;
+        let payload = spec.bind(payload)?;
+
+        let result: Value = self
+            .temporal
+            .query_workflow(workflow_id, run_id, query_name, Value::Object(payload))
+            .await
+            .map_err(|e| WorkflowServiceError::Temporal(e.to_string()))?;
+
+        Ok(QueryWorkflowResponse {
+            workflow_id: workflow_id.to_string(),
+            run_id: run_id.map(str::to_string),
+            query_name: query_name.to_string(),
+            result,
+        })
+    }
+
+    /// The run's workflow type and whether it's still running. Runs of
+    /// other tenants are reported as not found.
+    async fn workflow(
+        &self,
+        caller: &Caller<'_>,
+        workflow_id: &str,
+        run_id: Option<&str>,
+    ) -> WorkflowServiceResult<(String, bool)> {
+        let events = tenant_history(&self.temporal, caller.tenant_id, workflow_id, run_id).await?;
+        let workflow_type = started_workflow_type(&events).ok_or_else(|| {
+            WorkflowServiceError::Temporal(format!("History of workflow {} has no workflow type", workflow_id))
+        })?;
+
+        Ok((workflow_type, !run_is_closed(&events)))
+    }
+
+    fn authorize(
+        &self,
+        caller: &Caller<'_>,
+        workflow_type: &str,
+        kind: InteractionKind,
+        name: &str,
+    ) -> WorkflowServiceResult<&InteractionSpec> {
+        let spec = self.registry.lookup(workflow_type, kind, name).ok_or_else(|| {
+            WorkflowServiceError::NotFound(format!(
+                "Workflow type {} has no {} '{}'",
+                workflow_type,
+                kind.label(),
+                name
+            ))
+        })?;
+
+        if !spec.permits(caller.roles) {
+            return Err(WorkflowServiceError::Authorization(format!(
+                "{} '{}' requires one of the roles: {}",
+                capitalize(kind.label()),
+                name,
+                spec.allowed_roles.join(", ")
+            )));
+        }
+
+        Ok(spec)
+    }
+}
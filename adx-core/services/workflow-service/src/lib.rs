@@ -1,13 +1,27 @@
 pub mod activities;
+pub mod approvals;
+pub mod batches;
 pub mod config;
+pub mod controls;
+pub mod cron;
+pub mod dead_letters;
 pub mod error;
 pub mod handlers;
+pub mod interactions;
 pub mod management;
 pub mod models;
 pub mod monitoring;
+pub mod replay;
+pub mod repositories;
+pub mod schedules;
+pub mod search;
 pub mod server;
+pub mod task_queues;
 pub mod templates;
+pub mod transactions;
+pub mod triggers;
 pub mod versioning;
+pub mod visualization;
 pub mod worker;
 pub mod workflows;
 
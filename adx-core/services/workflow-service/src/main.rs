@@ -1,11 +1,13 @@
 use clap::{Parser, Subcommand};
-use adx_shared::{config::AppConfig, logging::init_logging};
+use adx_shared::{config::AppConfig, logging::init_logging, database::create_connection_pool};
 use workflow_service::{
     config::WorkflowServiceConfig,
     server::WorkflowServer,
     worker::WorkflowWorker,
     error::WorkflowServiceResult,
 };
+use adx_shared::temporal::AdxTemporalClient;
+use std::path::PathBuf;
 use tracing::{info, error};
 
 #[derive(Parser)]
@@ -22,6 +24,16 @@ enum Commands {
     Server,
     /// Start Temporal worker mode
     Worker,
+    /// Record a workflow run's history for the replay tests
+    RecordHistory {
+        workflow_id: String,
+        /// Defaults to the latest run
+        #[arg(long)]
+        run_id: Option<String>,
+        /// Directory the history is written to
+        #[arg(long, default_value = "tests/histories")]
+        output: PathBuf,
+    },
 }
 
 #[tokio::main]
@@ -38,7 +50,9 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
         Commands::Server => {
             info!("Starting Workflow Service HTTP server on port {}", workflow_config.server.port);
             
-            let server = WorkflowServer::new(workflow_config);
+            // Schedules and calendars are persisted; the other endpoints are stateless
+            let pool = create_connection_pool(&app_config.database).await?;
+            let server = WorkflowServer::new(workflow_config, pool).await?;
             if let Err(e) = server.run().await {
                 error!("Server error: {}", e);
                 return Err(e.into());
@@ -53,6 +67,13 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 return Err(e.into());
             }
         }
+        Commands::RecordHistory { workflow_id, run_id, output } => {
+            let temporal = AdxTemporalClient::new(workflow_config.temporal.client_config()).await?;
+            let history = temporal.record_history(&workflow_id, run_id.as_deref()).await?;
+            let path = history.save(&output)?;
+            
+            info!("Recorded {} activities of {} to {}", history.activities.len(), workflow_id, path.display());
+        }
     }
     
     Ok(())
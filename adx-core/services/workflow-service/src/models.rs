@@ -308,6 +308,27 @@ pub struct AuditReportSummary {
     pub report_format: String,
 }
 
+// Module Job Workflow Models
+/// A run of a module's background job, started by the job's schedule
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleJobRequest {
+    pub tenant_id: String,
+    pub job_id: Uuid,
+    pub module_id: String,
+    pub job_name: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ModuleJobResult {
+    pub job_id: Uuid,
+    pub run_id: Uuid,
+    /// Status module-service recorded for the run: succeeded, failed,
+    /// timed_out or skipped
+    pub status: String,
+    pub error: Option<String>,
+    pub completed_at: DateTime<Utc>,
+}
+
 // Workflow Progress Tracking
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WorkflowProgress {
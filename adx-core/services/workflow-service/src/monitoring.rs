@@ -2,10 +2,21 @@ use crate::{
     config::WorkflowServiceConfig,
     error::{WorkflowServiceError, WorkflowServiceResult},
     models::*,
+    repositories::{WorkflowCostRepository, WorkflowSlaRepository},
+    visualization::{closed_status, execution_status},
 };
-use chrono::{DateTime, Utc};
+use adx_shared::quotas::{QuotaClient, WORKFLOW_EXECUTIONS_QUOTA};
+use adx_shared::temporal::AdxTemporalClient;
+use adx_shared::ServiceError;
+use chrono::{DateTime, Datelike, TimeZone, Utc};
+use hmac::{Hmac, Mac};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, sync::Arc, time::Duration};
+use sha2::Sha256;
+use std::{
+    collections::{BTreeMap, HashMap, HashSet},
+    sync::{Arc, Mutex},
+    time::Duration,
+};
 use tracing::{info, warn, error};
 use uuid::Uuid;
 
@@ -466,6 +477,725 @@ impl AlertManager {
     }
 }
 
+/// Warning threshold, in percent of the limit, for budgets set without one
+const DEFAULT_BUDGET_WARN_PERCENT: u32 = 80;
+
+/// Usage recorded by the activities of one workflow run. Every downstream
+/// call is timed, and a call to an operation whose last call failed counts
+/// as a retry.
+pub struct RunCostMeter {
+    started_at: DateTime<Utc>,
+    state: Mutex<MeterState>,
+}
+
+#[derive(Default)]
+struct MeterState {
+    usage: RunUsage,
+    failing_operations: HashSet<String>,
+}
+
+impl Default for RunCostMeter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl RunCostMeter {
+    /// The run is taken to start when its meter is created
+    pub fn new() -> Self {
+        Self {
+            started_at: Utc::now(),
+            state: Mutex::default(),
+        }
+    }
+
+    pub fn started_at(&self) -> DateTime<Utc> {
+        self.started_at
+    }
+
+    pub fn record_call(&self, service: &str, operation: &str, duration: Duration, succeeded: bool) {
+        let mut state = self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+        let key = format!("{} {}", service, operation);
+        let retried = if succeeded {
+            state.failing_operations.remove(&key)
+        } else {
+            !state.failing_operations.insert(key)
+        };
+
+        let elapsed_ms = duration.as_millis() as u64;
+        let usage = &mut state.usage;
+        usage.activity_count += 1;
+        usage.activity_time_ms += elapsed_ms;
+        if retried {
+            usage.retry_count += 1;
+        }
+        if !succeeded {
+            usage.failed_calls += 1;
+        }
+
+        let service_usage = usage.services.entry(service.to_string()).or_default();
+        service_usage.calls += 1;
+        service_usage.time_ms += elapsed_ms;
+        if !succeeded {
+            service_usage.failed_calls += 1;
+        }
+    }
+
+    pub fn usage(&self) -> RunUsage {
+        self.state.lock().unwrap_or_else(|poisoned| poisoned.into_inner()).usage.clone()
+    }
+}
+
+/// Prices metered workflow runs, aggregates them per tenant and enforces
+/// the tenants' monthly budgets
+pub struct CostTracker {
+    config: Arc<WorkflowServiceConfig>,
+    repository: Arc<dyn WorkflowCostRepository>,
+    alerts: Arc<AlertManager>,
+    quotas: QuotaClient,
+}
+
+impl CostTracker {
+    pub fn new(
+        config: Arc<WorkflowServiceConfig>,
+        repository: Arc<dyn WorkflowCostRepository>,
+        alerts: Arc<AlertManager>,
+    ) -> Self {
+        let quotas = QuotaClient::from_url(&config.services.license_service);
+        Self {
+            config,
+            repository,
+            alerts,
+            quotas,
+        }
+    }
+
+    /// Compute and downstream API cost of a run's usage
+    pub fn price(&self, usage: &RunUsage) -> (f64, f64) {
+        let rates = &self.config.costs;
+        let compute_cost = usage.activity_time_ms as f64 / 1000.0 * rates.activity_second_cost;
+        let api_cost = usage
+            .services
+            .iter()
+            .map(|(service, service_usage)| service_usage.calls as f64 * rates.call_cost(service))
+            .sum();
+        (compute_cost, api_cost)
+    }
+
+    /// Price and store a finished run's usage and outcome, alerting when it
+    /// takes the tenant past its budget's warning threshold or limit
+    pub async fn record_run(
+        &self,
+        tenant_id: &str,
+        workflow_id: &str,
+        workflow_type: &str,
+        meter: &RunCostMeter,
+        succeeded: bool,
+    ) -> WorkflowServiceResult<RunCost> {
+        let usage = meter.usage();
+        let (compute_cost, api_cost) = self.price(&usage);
+        let cost = RunCost {
+            tenant_id: tenant_id.to_string(),
+            workflow_id: workflow_id.to_string(),
+            workflow_type: workflow_type.to_string(),
+            usage,
+            compute_cost,
+            api_cost,
+            total_cost: compute_cost + api_cost,
+            started_at: Some(meter.started_at()),
+            succeeded: Some(succeeded),
+            recorded_at: Utc::now(),
+        };
+        self.repository.record_run(&cost).await?;
+        info!(
+            "Workflow {} ({}) for tenant {} cost {:.4}",
+            workflow_id, workflow_type, tenant_id, cost.total_cost
+        );
+
+        let status = self.budget_status(tenant_id).await?;
+        if let Some(limit) = status.monthly_limit {
+            let spent_before = status.spent - cost.total_cost;
+            let warn_at = limit * status.warn_at_percent as f64 / 100.0;
+            let severity = if spent_before < limit && status.spent >= limit {
+                Some(IssueSeverity::Critical)
+            } else if spent_before < warn_at && status.spent >= warn_at {
+                Some(IssueSeverity::Warning)
+            } else {
+                None
+            };
+
+            if let Some(severity) = severity {
+                let issue = HealthIssue {
+                    issue_id: Uuid::new_v4().to_string(),
+                    workflow_id: workflow_id.to_string(),
+                    issue_type: IssueType::BudgetExceeded,
+                    severity,
+                    message: format!(
+                        "Tenant {} has spent {:.2} of its {:.2} monthly workflow budget",
+                        tenant_id, status.spent, limit
+                    ),
+                    detected_at: cost.recorded_at,
+                    suggested_actions: vec![
+                        "Review the tenant's cost report".to_string(),
+                        "Raise the budget or reduce non-critical workflow runs".to_string(),
+                    ],
+                };
+                if let Err(error) = self.alerts.trigger_alert(&issue).await {
+                    warn!("Failed to raise budget alert for tenant {}: {}", tenant_id, error);
+                }
+            }
+        }
+
+        Ok(cost)
+    }
+
+    pub async fn get_run_cost(&self, tenant_id: &str, workflow_id: &str) -> WorkflowServiceResult<RunCost> {
+        self.repository
+            .get_run(tenant_id, workflow_id)
+            .await?
+            .ok_or_else(|| WorkflowServiceError::NotFound(format!("No cost recorded for workflow {}", workflow_id)))
+    }
+
+    /// Costs of the tenant's runs recorded in a period, by default the
+    /// current budget month
+    pub async fn report(&self, tenant_id: &str, params: CostReportParams) -> WorkflowServiceResult<CostReport> {
+        let to = params.to.unwrap_or_else(Utc::now);
+        let from = params.from.unwrap_or_else(|| budget_period_start(to));
+        if from >= to {
+            return Err(WorkflowServiceError::Validation("from must be before to".to_string()));
+        }
+
+        let mut workflow_types = self.repository.costs_by_workflow_type(tenant_id, from, to).await?;
+        workflow_types.sort_by(|a, b| b.total_cost.total_cmp(&a.total_cost));
+
+        Ok(CostReport {
+            tenant_id: tenant_id.to_string(),
+            from,
+            to,
+            runs: workflow_types.iter().map(|t| t.runs).sum(),
+            activity_time_ms: workflow_types.iter().map(|t| t.activity_time_ms).sum(),
+            retry_count: workflow_types.iter().map(|t| t.retry_count).sum(),
+            api_calls: workflow_types.iter().map(|t| t.api_calls).sum(),
+            total_cost: workflow_types.iter().map(|t| t.total_cost).sum(),
+            workflow_types,
+            budget: self.budget_status(tenant_id).await?,
+        })
+    }
+
+    /// The tenant's budget and what it has spent this month
+    pub async fn budget_status(&self, tenant_id: &str) -> WorkflowServiceResult<BudgetStatus> {
+        let budget = self.repository.get_budget(tenant_id).await?.unwrap_or(WorkflowBudget {
+            monthly_limit: None,
+            warn_at_percent: DEFAULT_BUDGET_WARN_PERCENT,
+        });
+        let period_start = budget_period_start(Utc::now());
+        let spent = self.repository.spend_since(tenant_id, period_start).await?;
+
+        Ok(BudgetStatus {
+            monthly_limit: budget.monthly_limit,
+            warn_at_percent: budget.warn_at_percent,
+            period_start,
+            spent,
+            remaining: budget.monthly_limit.map(|limit| (limit - spent).max(0.0)),
+            exceeded: budget.monthly_limit.is_some_and(|limit| spent >= limit),
+        })
+    }
+
+    /// Set a tenant's monthly limit; a null limit removes it
+    pub async fn set_budget(&self, tenant_id: &str, request: SetWorkflowBudgetRequest) -> WorkflowServiceResult<BudgetStatus> {
+        if let Some(limit) = request.monthly_limit {
+            if !limit.is_finite() || limit < 0.0 {
+                return Err(WorkflowServiceError::Validation(
+                    "monthly_limit must be a non-negative amount".to_string(),
+                ));
+            }
+        }
+        let warn_at_percent = request.warn_at_percent.unwrap_or(DEFAULT_BUDGET_WARN_PERCENT);
+        if !(1..=100).contains(&warn_at_percent) {
+            return Err(WorkflowServiceError::Validation(
+                "warn_at_percent must be between 1 and 100".to_string(),
+            ));
+        }
+
+        let budget = WorkflowBudget {
+            monthly_limit: request.monthly_limit,
+            warn_at_percent,
+        };
+        self.repository.set_budget(tenant_id, &budget).await?;
+
+        info!("Set workflow budget for tenant {}: {:?}", tenant_id, budget);
+        self.budget_status(tenant_id).await
+    }
+
+    /// Refuse to start a non-critical workflow once the tenant has used up
+    /// this month's budget or this hour's workflow executions
+    pub async fn check_start(&self, tenant_id: &str, workflow_type: &str) -> WorkflowServiceResult<()> {
+        self.check_starts(tenant_id, workflow_type, 1).await
+    }
+
+    /// `check_start` for `count` runs of the workflow, e.g. a batch
+    pub async fn check_starts(&self, tenant_id: &str, workflow_type: &str, count: usize) -> WorkflowServiceResult<()> {
+        if self.config.costs.is_critical(workflow_type) {
+            return Ok(());
+        }
+
+        let status = self.budget_status(tenant_id).await?;
+        if let (true, Some(limit)) = (status.exceeded, status.monthly_limit) {
+            return Err(WorkflowServiceError::LimitExceeded(format!(
+                "Monthly workflow budget of {:.2} is used up ({:.2} spent); only critical workflows can start until {}",
+                limit,
+                status.spent,
+                next_budget_period(status.period_start).format("%Y-%m-%d")
+            )));
+        }
+
+        // License-service identifies tenants by UUID and is not required for
+        // workflows to start
+        if Uuid::parse_str(tenant_id).is_err() {
+            return Ok(());
+        }
+        match self.quotas.consume(tenant_id, WORKFLOW_EXECUTIONS_QUOTA, count as i64).await {
+            Ok(_) => Ok(()),
+            Err(ServiceError::QuotaExceeded(_)) => Err(WorkflowServiceError::LimitExceeded(format!(
+                "Hourly workflow execution quota is used up; {} more run(s) of {} cannot start",
+                count, workflow_type
+            ))),
+            Err(e) => {
+                warn!("Could not count workflow executions of tenant {}: {}", tenant_id, e);
+                Ok(())
+            }
+        }
+    }
+}
+
+/// Budgets run per calendar month in UTC
+fn budget_period_start(at: DateTime<Utc>) -> DateTime<Utc> {
+    Utc.with_ymd_and_hms(at.year(), at.month(), 1, 0, 0, 0)
+        .single()
+        .unwrap_or(at)
+}
+
+fn next_budget_period(period_start: DateTime<Utc>) -> DateTime<Utc> {
+    let (year, month) = match period_start.month() {
+        12 => (period_start.year() + 1, 1),
+        month => (period_start.year(), month + 1),
+    };
+    Utc.with_ymd_and_hms(year, month, 1, 0, 0, 0)
+        .single()
+        .unwrap_or(period_start)
+}
+
+pub const SLA_SIGNATURE_HEADER: &str = "X-ADX-Signature";
+pub const SLA_TIMESTAMP_HEADER: &str = "X-ADX-Timestamp";
+pub const SLA_EVENT_HEADER: &str = "X-ADX-Event";
+
+const DEFAULT_SLA_WINDOW_MINUTES: u32 = 60;
+const MAX_SLA_WINDOW_MINUTES: u32 = 7 * 24 * 60;
+const DEFAULT_SLA_MIN_RUNS: u32 = 10;
+const SLA_DELIVERY_ATTEMPTS: u32 = 3;
+const SLA_DELIVERY_TIMEOUT: Duration = Duration::from_secs(10);
+const DEFAULT_BREACH_PAGE_SIZE: u32 = 50;
+const MAX_BREACH_PAGE_SIZE: u32 = 200;
+
+/// Checks tenants' workflow SLAs against their runs. Inline runs are read
+/// from the cost records written when they finish; Temporal runs recorded in
+/// `workflow_executions` are checked against their history before a running
+/// one counts as overrunning. A breach is stored once, raised through the
+/// `AlertManager` and pushed to the SLA's webhook; failure-rate breaches
+/// stay open until the rate is back under the limit.
+pub struct SlaMonitor {
+    repository: Arc<dyn WorkflowSlaRepository>,
+    temporal: AdxTemporalClient,
+    alerts: Arc<AlertManager>,
+    client: reqwest::Client,
+}
+
+impl SlaMonitor {
+    pub fn new(
+        repository: Arc<dyn WorkflowSlaRepository>,
+        temporal: AdxTemporalClient,
+        alerts: Arc<AlertManager>,
+    ) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(SLA_DELIVERY_TIMEOUT)
+            .build()
+            .unwrap_or_else(|_| reqwest::Client::new());
+
+        Self {
+            repository,
+            temporal,
+            alerts,
+            client,
+        }
+    }
+
+    pub async fn list_slas(&self, tenant_id: &str) -> WorkflowServiceResult<Vec<WorkflowSla>> {
+        self.repository.list_slas(tenant_id).await
+    }
+
+    pub async fn get_sla(&self, tenant_id: &str, workflow_type: &str) -> WorkflowServiceResult<WorkflowSla> {
+        self.repository
+            .get_sla(tenant_id, workflow_type)
+            .await?
+            .ok_or_else(|| WorkflowServiceError::NotFound(format!("No SLA defined for {}", workflow_type)))
+    }
+
+    /// Create or replace the SLA of one workflow type
+    pub async fn set_sla(
+        &self,
+        tenant_id: &str,
+        user_id: Option<&str>,
+        workflow_type: &str,
+        request: SetWorkflowSlaRequest,
+    ) -> WorkflowServiceResult<WorkflowSla> {
+        let user_id = user_id.ok_or_else(|| {
+            WorkflowServiceError::Authorization("Defining an SLA requires a user".to_string())
+        })?;
+        if request.max_duration_seconds.is_none() && request.max_failure_rate.is_none() {
+            return Err(WorkflowServiceError::Validation(
+                "An SLA needs max_duration_seconds, max_failure_rate or both".to_string(),
+            ));
+        }
+        if request.max_duration_seconds == Some(0) {
+            return Err(WorkflowServiceError::Validation(
+                "max_duration_seconds must be positive".to_string(),
+            ));
+        }
+        if let Some(rate) = request.max_failure_rate {
+            if !rate.is_finite() || !(0.0..=100.0).contains(&rate) {
+                return Err(WorkflowServiceError::Validation(
+                    "max_failure_rate must be a percentage between 0 and 100".to_string(),
+                ));
+            }
+        }
+        let window_minutes = request.window_minutes.unwrap_or(DEFAULT_SLA_WINDOW_MINUTES);
+        if !(1..=MAX_SLA_WINDOW_MINUTES).contains(&window_minutes) {
+            return Err(WorkflowServiceError::Validation(format!(
+                "window_minutes must be between 1 and {}",
+                MAX_SLA_WINDOW_MINUTES
+            )));
+        }
+        let min_runs = request.min_runs.unwrap_or(DEFAULT_SLA_MIN_RUNS).max(1);
+        if let Some(url) = &request.webhook_url {
+            let local = url.starts_with("http://localhost") || url.starts_with("http://127.0.0.1");
+            if !url.starts_with("https://") && !local {
+                return Err(WorkflowServiceError::Validation("webhook_url must use https".to_string()));
+            }
+        }
+
+        let existing = self.repository.get_sla(tenant_id, workflow_type).await?;
+        let now = Utc::now();
+        let sla = WorkflowSla {
+            tenant_id: tenant_id.to_string(),
+            workflow_type: workflow_type.to_string(),
+            max_duration_seconds: request.max_duration_seconds,
+            max_failure_rate: request.max_failure_rate,
+            window_minutes,
+            min_runs,
+            webhook_url: request.webhook_url,
+            webhook_secret: request.webhook_secret,
+            is_enabled: request.is_enabled.unwrap_or(true),
+            created_by: existing.as_ref().map_or_else(|| user_id.to_string(), |sla| sla.created_by.clone()),
+            created_at: existing.as_ref().map_or(now, |sla| sla.created_at),
+            updated_at: now,
+        };
+        self.repository.set_sla(&sla).await?;
+
+        info!("Set SLA for {} of tenant {}", workflow_type, tenant_id);
+        Ok(sla)
+    }
+
+    pub async fn delete_sla(&self, tenant_id: &str, workflow_type: &str) -> WorkflowServiceResult<()> {
+        if !self.repository.delete_sla(tenant_id, workflow_type).await? {
+            return Err(WorkflowServiceError::NotFound(format!("No SLA defined for {}", workflow_type)));
+        }
+        Ok(())
+    }
+
+    /// Check a workflow type's SLA after one of its runs has finished
+    pub async fn run_finished(&self, tenant_id: &str, workflow_type: &str) -> WorkflowServiceResult<()> {
+        if let Some(sla) = self.repository.get_sla(tenant_id, workflow_type).await? {
+            if sla.is_enabled {
+                self.evaluate(&sla).await?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Evaluate every SLA of the tenant and report where each stands
+    pub async fn status(&self, tenant_id: &str) -> WorkflowServiceResult<SlaStatusResponse> {
+        let mut slas = Vec::new();
+        for sla in self.repository.list_slas(tenant_id).await? {
+            let status = if sla.is_enabled {
+                self.evaluate(&sla).await?
+            } else {
+                WorkflowSlaStatus {
+                    state: SlaState::Disabled,
+                    window_start: Utc::now() - chrono::Duration::minutes(sla.window_minutes as i64),
+                    stats: SlaWindowStats::default(),
+                    failure_rate: None,
+                    running_over_limit: 0,
+                    finished_over_limit: 0,
+                    open_breaches: Vec::new(),
+                    sla,
+                }
+            };
+            slas.push(status);
+        }
+
+        Ok(SlaStatusResponse {
+            tenant_id: tenant_id.to_string(),
+            evaluated_at: Utc::now(),
+            breached: slas.iter().filter(|s| s.state == SlaState::Breached).count() as u32,
+            slas,
+        })
+    }
+
+    pub async fn list_breaches(&self, tenant_id: &str, params: SlaBreachParams) -> WorkflowServiceResult<SlaBreachListResponse> {
+        let limit = params.limit.unwrap_or(DEFAULT_BREACH_PAGE_SIZE).clamp(1, MAX_BREACH_PAGE_SIZE);
+        let offset = params.offset.unwrap_or(0);
+        let (breaches, total_count) = self
+            .repository
+            .list_breaches(tenant_id, params.status, params.workflow_type.as_deref(), limit, offset)
+            .await?;
+
+        Ok(SlaBreachListResponse {
+            breaches,
+            total_count,
+            limit,
+            offset,
+        })
+    }
+
+    async fn evaluate(&self, sla: &WorkflowSla) -> WorkflowServiceResult<WorkflowSlaStatus> {
+        let now = Utc::now();
+        let window_start = now - chrono::Duration::minutes(sla.window_minutes as i64);
+        let mut running_over_limit = 0;
+        let mut finished_over_limit = 0;
+
+        if let Some(max_seconds) = sla.max_duration_seconds {
+            let max_seconds = max_seconds as i64;
+            let started_before = now - chrono::Duration::seconds(max_seconds);
+            for run in self
+                .repository
+                .running_overruns(&sla.tenant_id, &sla.workflow_type, started_before)
+                .await?
+            {
+                if self.closed_in_temporal(&sla.tenant_id, &run).await? {
+                    continue;
+                }
+                running_over_limit += 1;
+                let overrun = (now - run.started_at).num_seconds() as f64;
+                let breach = SlaBreach::open(sla, SlaBreachKind::Duration, Some(&run.workflow_id), max_seconds as f64, overrun, now);
+                self.open_breach(sla, breach).await?;
+            }
+
+            for run in self
+                .repository
+                .closed_overruns(&sla.tenant_id, &sla.workflow_type, window_start, max_seconds)
+                .await?
+            {
+                finished_over_limit += 1;
+                let observed = run.duration_seconds as f64;
+                let mut breach =
+                    SlaBreach::open(sla, SlaBreachKind::Duration, Some(&run.workflow_id), max_seconds as f64, observed, now);
+                breach.status = SlaBreachStatus::Resolved;
+                breach.resolved_at = Some(run.closed_at);
+                self.open_breach(sla, breach).await?;
+            }
+            self.repository
+                .resolve_finished_overruns(&sla.tenant_id, &sla.workflow_type, now)
+                .await?;
+        }
+
+        let stats = self
+            .repository
+            .window_stats(&sla.tenant_id, &sla.workflow_type, window_start)
+            .await?;
+        let failure_rate = (stats.runs > 0).then(|| stats.failed_runs as f64 / stats.runs as f64 * 100.0);
+        if let (Some(max_rate), Some(rate)) = (sla.max_failure_rate, failure_rate) {
+            if stats.runs >= sla.min_runs {
+                if rate > max_rate {
+                    let breach = SlaBreach::open(sla, SlaBreachKind::FailureRate, None, max_rate, rate, now);
+                    self.open_breach(sla, breach).await?;
+                } else if let Some(breach) = self
+                    .repository
+                    .resolve_failure_rate(&sla.tenant_id, &sla.workflow_type, now)
+                    .await?
+                {
+                    info!(
+                        "Failure rate of {} for tenant {} is back to {:.1}%",
+                        sla.workflow_type, sla.tenant_id, rate
+                    );
+                    self.notify(sla, breach, SLA_RECOVERED_EVENT);
+                }
+            }
+        }
+
+        let (open_breaches, _) = self
+            .repository
+            .list_breaches(&sla.tenant_id, Some(SlaBreachStatus::Open), Some(&sla.workflow_type), MAX_BREACH_PAGE_SIZE, 0)
+            .await?;
+        let state = if !open_breaches.is_empty() || finished_over_limit > 0 {
+            SlaState::Breached
+        } else if sla.max_duration_seconds.is_none() && stats.runs < sla.min_runs {
+            SlaState::InsufficientData
+        } else {
+            SlaState::Met
+        };
+
+        Ok(WorkflowSlaStatus {
+            sla: sla.clone(),
+            state,
+            window_start,
+            stats,
+            failure_rate,
+            running_over_limit,
+            finished_over_limit,
+            open_breaches,
+        })
+    }
+
+    /// Whether a run recorded as running has closed in Temporal; closed
+    /// runs are marked so. A run whose history can't be read is taken to
+    /// be running.
+    async fn closed_in_temporal(&self, tenant_id: &str, run: &RunningExecution) -> WorkflowServiceResult<bool> {
+        let events = match self
+            .temporal
+            .get_workflow_history(&run.workflow_id, Some(&run.run_id))
+            .await
+        {
+            Ok(events) => events,
+            Err(error) => {
+                warn!("Could not check run {} of {}: {}", run.run_id, run.workflow_id, error);
+                return Ok(false);
+            }
+        };
+        let Some(status) = closed_status(&events) else {
+            return Ok(false);
+        };
+
+        let closed_at = events.last().map(|event| event.event_time).unwrap_or_else(Utc::now);
+        self.repository
+            .close_execution(tenant_id, &run.workflow_id, &run.run_id, execution_status(status), closed_at)
+            .await?;
+        Ok(true)
+    }
+
+    /// Store a breach and notify it, unless it was already recorded
+    async fn open_breach(&self, sla: &WorkflowSla, breach: SlaBreach) -> WorkflowServiceResult<()> {
+        if !self.repository.record_breach(&breach).await? {
+            return Ok(());
+        }
+
+        let (issue_type, message) = match breach.kind {
+            SlaBreachKind::Duration => (
+                IssueType::LongRunning,
+                format!(
+                    "Workflow {} ({}) ran {}s, over its {}s SLA",
+                    breach.workflow_id.as_deref().unwrap_or_default(),
+                    breach.workflow_type,
+                    breach.observed as i64,
+                    breach.threshold as i64
+                ),
+            ),
+            SlaBreachKind::FailureRate => (
+                IssueType::HighErrorRate,
+                format!(
+                    "{:.1}% of {} runs failed in the last {} minutes, over the {:.1}% SLA",
+                    breach.observed, breach.workflow_type, sla.window_minutes, breach.threshold
+                ),
+            ),
+        };
+        let issue = HealthIssue {
+            issue_id: breach.breach_id.clone(),
+            workflow_id: breach.workflow_id.clone().unwrap_or_default(),
+            issue_type,
+            severity: IssueSeverity::Warning,
+            message,
+            detected_at: breach.detected_at,
+            suggested_actions: vec![
+                format!("Review recent {} runs of tenant {}", breach.workflow_type, breach.tenant_id),
+                "Check the health of the services the workflow calls".to_string(),
+            ],
+        };
+        if let Err(error) = self.alerts.trigger_alert(&issue).await {
+            warn!("Failed to raise SLA alert {}: {}", breach.breach_id, error);
+        }
+
+        self.notify(sla, breach, SLA_BREACHED_EVENT);
+        Ok(())
+    }
+
+    /// Push a breach event to the SLA's webhook in the background
+    fn notify(&self, sla: &WorkflowSla, breach: SlaBreach, event: &'static str) {
+        let Some(url) = sla.webhook_url.clone() else {
+            return;
+        };
+        let secret = sla.webhook_secret.clone();
+        let client = self.client.clone();
+        let repository = self.repository.clone();
+
+        tokio::spawn(async move {
+            let body = match serde_json::to_vec(&serde_json::json!({ "event": event, "breach": breach })) {
+                Ok(body) => body,
+                Err(error) => {
+                    warn!("Failed to serialize SLA breach {}: {}", breach.breach_id, error);
+                    return;
+                }
+            };
+
+            let mut last_error = None;
+            for attempt in 1..=SLA_DELIVERY_ATTEMPTS {
+                if attempt > 1 {
+                    tokio::time::sleep(Duration::from_secs(4u64.pow(attempt - 2))).await;
+                }
+                let timestamp = Utc::now().timestamp();
+                let mut request = client
+                    .post(&url)
+                    .header("Content-Type", "application/json")
+                    .header(SLA_EVENT_HEADER, event)
+                    .header(SLA_TIMESTAMP_HEADER, timestamp.to_string());
+                if let Some(secret) = &secret {
+                    request = request.header(SLA_SIGNATURE_HEADER, sign_sla_payload(secret, timestamp, &body));
+                }
+
+                last_error = match request.body(body.clone()).send().await {
+                    Ok(response) if response.status().is_success() => None,
+                    Ok(response) => Some(format!("Receiver returned {}", response.status())),
+                    Err(error) => Some(error.to_string()),
+                };
+                if last_error.is_none() {
+                    break;
+                }
+            }
+
+            if let Some(error) = &last_error {
+                warn!("Giving up on SLA webhook for breach {}: {}", breach.breach_id, error);
+            }
+            if let Err(error) = repository
+                .record_notification(&breach.tenant_id, &breach.breach_id, last_error.as_deref())
+                .await
+            {
+                warn!("Failed to record notification of SLA breach {}: {}", breach.breach_id, error);
+            }
+        });
+    }
+}
+
+const SLA_BREACHED_EVENT: &str = "workflow.sla_breached";
+const SLA_RECOVERED_EVENT: &str = "workflow.sla_recovered";
+
+/// HMAC-SHA256 over "{timestamp}.{body}", hex encoded, as tenant webhooks
+/// are signed
+fn sign_sla_payload(secret: &str, timestamp: i64, body: &[u8]) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes()).expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body);
+    format!("sha256={:x}", mac.finalize().into_bytes())
+}
+
 // Data structures for monitoring
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -542,6 +1272,8 @@ pub enum IssueType {
     HighErrorRate,
     ResourceExhaustion,
     PerformanceDegradation,
+    DeadLettered,
+    BudgetExceeded,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq)]
@@ -713,4 +1445,278 @@ pub struct RetryInfo {
     pub max_attempts: u32,
     pub next_retry_at: Option<DateTime<Utc>>,
     pub backoff_duration: Duration,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct RunUsage {
+    /// Downstream calls made by activities
+    pub activity_count: u32,
+    pub activity_time_ms: u64,
+    pub retry_count: u32,
+    pub failed_calls: u32,
+    pub services: BTreeMap<String, ServiceUsage>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ServiceUsage {
+    pub calls: u32,
+    pub failed_calls: u32,
+    pub time_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct RunCost {
+    pub tenant_id: String,
+    pub workflow_id: String,
+    pub workflow_type: String,
+    pub usage: RunUsage,
+    pub compute_cost: f64,
+    pub api_cost: f64,
+    pub total_cost: f64,
+    /// Unknown for runs recorded before outcomes were kept
+    pub started_at: Option<DateTime<Utc>>,
+    pub succeeded: Option<bool>,
+    pub recorded_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowTypeCost {
+    pub workflow_type: String,
+    pub runs: u32,
+    pub activity_time_ms: u64,
+    pub retry_count: u32,
+    pub api_calls: u32,
+    pub total_cost: f64,
+}
+
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct CostReportParams {
+    pub from: Option<DateTime<Utc>>,
+    pub to: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CostReport {
+    pub tenant_id: String,
+    pub from: DateTime<Utc>,
+    pub to: DateTime<Utc>,
+    pub runs: u32,
+    pub activity_time_ms: u64,
+    pub retry_count: u32,
+    pub api_calls: u32,
+    pub total_cost: f64,
+    /// Most expensive first
+    pub workflow_types: Vec<WorkflowTypeCost>,
+    pub budget: BudgetStatus,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct WorkflowBudget {
+    /// None when the tenant is not limited
+    pub monthly_limit: Option<f64>,
+    pub warn_at_percent: u32,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BudgetStatus {
+    pub monthly_limit: Option<f64>,
+    pub warn_at_percent: u32,
+    pub period_start: DateTime<Utc>,
+    pub spent: f64,
+    pub remaining: Option<f64>,
+    pub exceeded: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetWorkflowBudgetRequest {
+    pub monthly_limit: Option<f64>,
+    pub warn_at_percent: Option<u32>,
+}
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowSla {
+    pub tenant_id: String,
+    pub workflow_type: String,
+    pub max_duration_seconds: Option<u64>,
+    /// Percentage of runs in the window allowed to fail
+    pub max_failure_rate: Option<f64>,
+    pub window_minutes: u32,
+    /// Runs the window needs before the failure rate is judged
+    pub min_runs: u32,
+    pub webhook_url: Option<String>,
+    #[serde(skip_serializing)]
+    pub webhook_secret: Option<String>,
+    pub is_enabled: bool,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetWorkflowSlaRequest {
+    pub max_duration_seconds: Option<u64>,
+    pub max_failure_rate: Option<f64>,
+    pub window_minutes: Option<u32>,
+    pub min_runs: Option<u32>,
+    pub webhook_url: Option<String>,
+    pub webhook_secret: Option<String>,
+    pub is_enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SlaBreachKind {
+    Duration,
+    FailureRate,
+}
+
+impl SlaBreachKind {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SlaBreachKind::Duration => "duration",
+            SlaBreachKind::FailureRate => "failure_rate",
+        }
+    }
+
+    pub fn parse(value: &str) -> WorkflowServiceResult<Self> {
+        match value {
+            "duration" => Ok(SlaBreachKind::Duration),
+            "failure_rate" => Ok(SlaBreachKind::FailureRate),
+            other => Err(WorkflowServiceError::Internal(format!("Unknown SLA breach kind: {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SlaBreachStatus {
+    Open,
+    Resolved,
+}
+
+impl SlaBreachStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SlaBreachStatus::Open => "open",
+            SlaBreachStatus::Resolved => "resolved",
+        }
+    }
+
+    pub fn parse(value: &str) -> WorkflowServiceResult<Self> {
+        match value {
+            "open" => Ok(SlaBreachStatus::Open),
+            "resolved" => Ok(SlaBreachStatus::Resolved),
+            other => Err(WorkflowServiceError::Internal(format!("Unknown SLA breach status: {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlaBreach {
+    pub breach_id: String,
+    pub tenant_id: String,
+    pub workflow_type: String,
+    pub kind: SlaBreachKind,
+    /// The overrunning run of a duration breach
+    pub workflow_id: Option<String>,
+    /// Seconds for duration breaches, a percentage for failure-rate ones
+    pub threshold: f64,
+    pub observed: f64,
+    pub status: SlaBreachStatus,
+    pub detected_at: DateTime<Utc>,
+    pub resolved_at: Option<DateTime<Utc>>,
+    pub notified_at: Option<DateTime<Utc>>,
+    pub notification_error: Option<String>,
+}
+
+impl SlaBreach {
+    fn open(
+        sla: &WorkflowSla,
+        kind: SlaBreachKind,
+        workflow_id: Option<&str>,
+        threshold: f64,
+        observed: f64,
+        detected_at: DateTime<Utc>,
+    ) -> Self {
+        Self {
+            breach_id: Uuid::new_v4().to_string(),
+            tenant_id: sla.tenant_id.clone(),
+            workflow_type: sla.workflow_type.clone(),
+            kind,
+            workflow_id: workflow_id.map(str::to_string),
+            threshold,
+            observed,
+            status: SlaBreachStatus::Open,
+            detected_at,
+            resolved_at: None,
+            notified_at: None,
+            notification_error: None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SlaWindowStats {
+    pub runs: u32,
+    pub failed_runs: u32,
+    pub avg_duration_seconds: Option<f64>,
+}
+
+#[derive(Debug, Clone)]
+pub struct RunningExecution {
+    pub workflow_id: String,
+    pub run_id: String,
+    pub started_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone)]
+pub struct ClosedOverrun {
+    pub workflow_id: String,
+    pub duration_seconds: i64,
+    pub closed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum SlaState {
+    Met,
+    Breached,
+    /// Too few runs in the window to judge the failure rate
+    InsufficientData,
+    Disabled,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkflowSlaStatus {
+    pub sla: WorkflowSla,
+    pub state: SlaState,
+    pub window_start: DateTime<Utc>,
+    pub stats: SlaWindowStats,
+    pub failure_rate: Option<f64>,
+    pub running_over_limit: u32,
+    pub finished_over_limit: u32,
+    pub open_breaches: Vec<SlaBreach>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SlaStatusResponse {
+    pub tenant_id: String,
+    pub evaluated_at: DateTime<Utc>,
+    pub breached: u32,
+    pub slas: Vec<WorkflowSlaStatus>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SlaBreachParams {
+    pub workflow_type: Option<String>,
+    pub status: Option<SlaBreachStatus>,
+    pub limit: Option<u32>,
+    pub offset: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct SlaBreachListResponse {
+    pub breaches: Vec<SlaBreach>,
+    pub total_count: u64,
+    pub limit: u32,
+    pub offset: u32,
 }
\ No newline at end of file
@@ -0,0 +1,196 @@
+//! Replay of recorded workflow runs against the current workflow code.
+//!
+//! Histories recorded from staging with `workflow-service record-history`
+//! are kept under `tests/histories` and replayed by `cargo test`. Each
+//! workflow runs against the recorded activity outcomes instead of the
+//! downstream services; a change that calls activities in a different
+//! order, with different input, or that ends differently would break runs
+//! already in flight when it is deployed, and fails the replay.
+
+use crate::{
+    activities::*,
+    batches::run_child_workflow,
+    error::{WorkflowServiceError, WorkflowServiceResult},
+    models::{ModuleJobRequest, ModuleJobResult},
+    templates::SUB_WORKFLOW_TYPES,
+};
+use adx_shared::temporal::{NondeterminismError, RecordedHistory, RecordedOutcome, ReplayCursor};
+use async_trait::async_trait;
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use tracing::debug;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ReplayReport {
+    pub workflow_id: String,
+    pub workflow_type: String,
+    pub activities_replayed: usize,
+    /// Error the replayed run ended with, matching the recorded failure
+    pub workflow_error: Option<String>,
+}
+
+/// Run a recorded history through the current workflow code
+pub async fn replay_history(history: RecordedHistory) -> WorkflowServiceResult<ReplayReport> {
+    let workflow_type = history.workflow_type.clone();
+    if !SUB_WORKFLOW_TYPES.contains(&workflow_type.as_str()) {
+        return Err(WorkflowServiceError::Validation(format!(
+            "Workflow type '{}' cannot be replayed",
+            workflow_type
+        )));
+    }
+
+    let workflow_id = history.workflow_id.clone();
+    let input = history.input.clone();
+    let activities_replayed = history.activities.len();
+    let activities = ReplayActivities::new(history);
+
+    let result = run_child_workflow(&workflow_type, input, &activities).await;
+    let workflow_error = result.err().map(|error| error.to_string());
+    activities.cursor.finish(workflow_error.is_none()).map_err(|mismatch| {
+        let detail = match (&mismatch, &workflow_error) {
+            (NondeterminismError::OutcomeChanged { .. }, Some(error)) => format!("{}: {}", mismatch, error),
+            _ => mismatch.to_string(),
+        };
+        WorkflowServiceError::WorkflowExecution(format!("{} does not replay: {}", workflow_id, detail))
+    })?;
+
+    debug!("Replayed {} ({}) with {} activities", workflow_id, workflow_type, activities_replayed);
+    Ok(ReplayReport {
+        workflow_id,
+        workflow_type,
+        activities_replayed,
+        workflow_error,
+    })
+}
+
+/// Activities answered from a recorded run rather than the services
+pub struct ReplayActivities {
+    cursor: ReplayCursor,
+}
+
+impl ReplayActivities {
+    pub fn new(history: RecordedHistory) -> Self {
+        Self {
+            cursor: ReplayCursor::new(history),
+        }
+    }
+
+    fn replay<Req: Serialize, Res: DeserializeOwned>(&self, activity_type: &str, request: &Req) -> WorkflowServiceResult<Res> {
+        let input = serde_json::to_value(request)?;
+        let outcome = self
+            .cursor
+            .next_activity(activity_type, &input)
+            .map_err(|mismatch| WorkflowServiceError::WorkflowExecution(mismatch.to_string()))?;
+
+        match outcome {
+            RecordedOutcome::Completed { result } => Ok(serde_json::from_value(result)?),
+            RecordedOutcome::Failed { message } => Err(WorkflowServiceError::ActivityExecution(message)),
+        }
+    }
+}
+
+#[async_trait]
+impl CrossServiceActivities for ReplayActivities {
+    async fn create_user_account(&self, request: CreateUserAccountRequest) -> WorkflowServiceResult<CreateUserAccountResult> {
+        self.replay("create_user_account", &request)
+    }
+
+    async fn validate_user_credentials(&self, request: ValidateUserCredentialsRequest) -> WorkflowServiceResult<ValidateUserCredentialsResult> {
+        self.replay("validate_user_credentials", &request)
+    }
+
+    async fn update_user_session(&self, request: UpdateUserSessionRequest) -> WorkflowServiceResult<UpdateUserSessionResult> {
+        self.replay("update_user_session", &request)
+    }
+
+    async fn revoke_user_sessions(&self, request: RevokeUserSessionsRequest) -> WorkflowServiceResult<RevokeUserSessionsResult> {
+        self.replay("revoke_user_sessions", &request)
+    }
+
+    async fn delete_user_account(&self, request: DeleteUserAccountRequest) -> WorkflowServiceResult<DeleteUserAccountResult> {
+        self.replay("delete_user_account", &request)
+    }
+
+    async fn create_user_profile(&self, request: CreateUserProfileRequest) -> WorkflowServiceResult<CreateUserProfileResult> {
+        self.replay("create_user_profile", &request)
+    }
+
+    async fn update_user_tenant_context(&self, request: UpdateUserTenantContextRequest) -> WorkflowServiceResult<UpdateUserTenantContextResult> {
+        self.replay("update_user_tenant_context", &request)
+    }
+
+    async fn get_user_data_for_export(&self, request: GetUserDataRequest) -> WorkflowServiceResult<GetUserDataResult> {
+        self.replay("get_user_data_for_export", &request)
+    }
+
+    async fn delete_user_data(&self, request: DeleteUserDataRequest) -> WorkflowServiceResult<DeleteUserDataResult> {
+        self.replay("delete_user_data", &request)
+    }
+
+    async fn validate_tenant_access(&self, request: ValidateTenantAccessRequest) -> WorkflowServiceResult<ValidateTenantAccessResult> {
+        self.replay("validate_tenant_access", &request)
+    }
+
+    async fn get_tenant_context(&self, request: GetTenantContextRequest) -> WorkflowServiceResult<GetTenantContextResult> {
+        self.replay("get_tenant_context", &request)
+    }
+
+    async fn update_tenant_user_membership(&self, request: UpdateTenantUserMembershipRequest) -> WorkflowServiceResult<UpdateTenantUserMembershipResult> {
+        self.replay("update_tenant_user_membership", &request)
+    }
+
+    async fn get_tenant_data_for_migration(&self, request: GetTenantDataRequest) -> WorkflowServiceResult<GetTenantDataResult> {
+        self.replay("get_tenant_data_for_migration", &request)
+    }
+
+    async fn create_tenant(&self, request: CreateTenantRequest) -> WorkflowServiceResult<CreateTenantResult> {
+        self.replay("create_tenant", &request)
+    }
+
+    async fn delete_tenant(&self, request: DeleteTenantRequest) -> WorkflowServiceResult<DeleteTenantResult> {
+        self.replay("delete_tenant", &request)
+    }
+
+    async fn setup_user_file_workspace(&self, request: SetupUserFileWorkspaceRequest) -> WorkflowServiceResult<SetupUserFileWorkspaceResult> {
+        self.replay("setup_user_file_workspace", &request)
+    }
+
+    async fn migrate_user_files(&self, request: MigrateUserFilesRequest) -> WorkflowServiceResult<MigrateUserFilesResult> {
+        self.replay("migrate_user_files", &request)
+    }
+
+    async fn export_user_files(&self, request: ExportUserFilesRequest) -> WorkflowServiceResult<ExportUserFilesResult> {
+        self.replay("export_user_files", &request)
+    }
+
+    async fn delete_user_files(&self, request: DeleteUserFilesRequest) -> WorkflowServiceResult<DeleteUserFilesResult> {
+        self.replay("delete_user_files", &request)
+    }
+
+    async fn provision_license(&self, request: ProvisionLicenseRequest) -> WorkflowServiceResult<ProvisionLicenseResult> {
+        self.replay("provision_license", &request)
+    }
+
+    async fn revoke_license(&self, request: RevokeLicenseRequest) -> WorkflowServiceResult<RevokeLicenseResult> {
+        self.replay("revoke_license", &request)
+    }
+
+    async fn run_module_job(&self, request: ModuleJobRequest) -> WorkflowServiceResult<ModuleJobResult> {
+        self.replay("run_module_job", &request)
+    }
+
+    async fn coordinate_service_health_check(&self, services: Vec<String>) -> WorkflowServiceResult<ServiceHealthCheckResult> {
+        self.replay("coordinate_service_health_check", &services)
+    }
+
+    async fn create_cross_service_backup(&self, request: CreateBackupRequest) -> WorkflowServiceResult<CreateBackupResult> {
+        self.replay("create_cross_service_backup", &request)
+    }
+
+    async fn restore_from_backup(&self, request: RestoreBackupRequest) -> WorkflowServiceResult<RestoreBackupResult> {
+        self.replay("restore_from_backup", &request)
+    }
+
+    async fn send_notification(&self, request: SendNotificationRequest) -> WorkflowServiceResult<SendNotificationResult> {
+        self.replay("send_notification", &request)
+    }
+}
@@ -0,0 +1,3295 @@
+use async_trait::async_trait;
+use chrono::{DateTime, NaiveDate, Utc};
+use sqlx::{postgres::PgRow, PgPool, Postgres, Row, Transaction};
+use std::collections::HashMap;
+use uuid::Uuid;
+
+use crate::approvals::{ApprovalStatus, ApprovalTask, Assignees, CreateApprovalTaskRequest};
+use crate::batches::{BatchChild, BatchChildStatus, BatchCounts, BatchStatus, WorkflowBatch};
+use crate::controls::{ControlAction, WorkflowControlRecord};
+use crate::dead_letters::{CaptureDeadLetterRequest, DeadLetter, DeadLetterStatus};
+use crate::error::{WorkflowServiceError, WorkflowServiceResult};
+use crate::monitoring::{
+    ClosedOverrun, RunCost, RunUsage, RunningExecution, SlaBreach, SlaBreachKind, SlaBreachStatus, SlaWindowStats,
+    WorkflowBudget, WorkflowSla, WorkflowTypeCost,
+};
+use crate::schedules::{ScheduleLimits, ScheduleState, WorkflowCalendar, WorkflowSchedule};
+use crate::task_queues::TaskQueueAssignment;
+use crate::templates::{
+    GetTemplatesParams, TemplateInstance, TemplateUsageStats, UsageTrend, WorkflowTemplate, WorkflowTemplateSummary,
+    TEMPLATE_WORKFLOW_TYPE,
+};
+use crate::triggers::{FiringStatus, QueuedEvent, TriggerEvent, TriggerEventStatus, TriggerFiring, WorkflowTrigger};
+use crate::versioning::{MigrationStatus, RolloutStrategy, VersionRollout, VersionStatus, VersionedExecution, WorkflowVersionRecord};
+use adx_shared::database::isolation::TENANT_ID_SETTING;
+
+#[async_trait]
+pub trait WorkflowScheduleRepository: Send + Sync {
+    async fn create_schedule(&self, schedule: &WorkflowSchedule) -> WorkflowServiceResult<()>;
+    async fn get_schedule(&self, tenant_id: &str, schedule_id: &str) -> WorkflowServiceResult<Option<WorkflowSchedule>>;
+    async fn list_schedules(&self, tenant_id: &str) -> WorkflowServiceResult<Vec<WorkflowSchedule>>;
+    async fn list_schedules_on_calendar(&self, tenant_id: &str, calendar_id: &str) -> WorkflowServiceResult<Vec<WorkflowSchedule>>;
+    async fn count_schedules(&self, tenant_id: &str) -> WorkflowServiceResult<u32>;
+    async fn update_schedule(&self, schedule: &WorkflowSchedule) -> WorkflowServiceResult<()>;
+    async fn delete_schedule(&self, tenant_id: &str, schedule_id: &str) -> WorkflowServiceResult<bool>;
+
+    async fn create_calendar(&self, calendar: &WorkflowCalendar) -> WorkflowServiceResult<()>;
+    async fn get_calendar(&self, tenant_id: &str, calendar_id: &str) -> WorkflowServiceResult<Option<WorkflowCalendar>>;
+    async fn list_calendars(&self, tenant_id: &str) -> WorkflowServiceResult<Vec<WorkflowCalendar>>;
+    async fn update_calendar(&self, calendar: &WorkflowCalendar) -> WorkflowServiceResult<()>;
+    async fn delete_calendar(&self, tenant_id: &str, calendar_id: &str) -> WorkflowServiceResult<bool>;
+
+    async fn get_limits(&self, tenant_id: &str) -> WorkflowServiceResult<Option<ScheduleLimits>>;
+    async fn set_limits(&self, tenant_id: &str, limits: &ScheduleLimits) -> WorkflowServiceResult<()>;
+}
+
+/// Templates are readable by their tenant and, when public, by every
+/// tenant; only the owning tenant may change or delete them
+#[async_trait]
+pub trait WorkflowTemplateRepository: Send + Sync {
+    async fn create_template(&self, template: &WorkflowTemplate) -> WorkflowServiceResult<()>;
+    async fn get_template(&self, tenant_id: &str, template_id: &str) -> WorkflowServiceResult<Option<WorkflowTemplate>>;
+    async fn list_templates(&self, tenant_id: &str, params: &GetTemplatesParams) -> WorkflowServiceResult<Vec<WorkflowTemplateSummary>>;
+    async fn update_template(&self, template: &WorkflowTemplate) -> WorkflowServiceResult<()>;
+    async fn delete_template(&self, tenant_id: &str, template_id: &str) -> WorkflowServiceResult<bool>;
+
+    async fn record_instance(&self, instance: &TemplateInstance) -> WorkflowServiceResult<()>;
+    async fn active_workflows(&self, tenant_id: &str, template_id: &str) -> WorkflowServiceResult<Vec<String>>;
+    async fn template_usage(&self, tenant_id: &str, template_id: &str) -> WorkflowServiceResult<TemplateUsageStats>;
+}
+
+/// Approval tasks only leave `pending` once, so every state change is
+/// conditional on the task still being pending and reports whether it won
+#[async_trait]
+pub trait WorkflowApprovalRepository: Send + Sync {
+    /// Returns the task and whether it was created; a task that already
+    /// exists for the workflow step is returned unchanged
+    async fn create_approval(&self, request: &CreateApprovalTaskRequest) -> WorkflowServiceResult<(ApprovalTask, bool)>;
+    async fn get_approval(&self, tenant_id: &str, approval_id: &str) -> WorkflowServiceResult<Option<ApprovalTask>>;
+    async fn list_assigned(
+        &self,
+        tenant_id: &str,
+        user_id: &str,
+        roles: &[String],
+        status: ApprovalStatus,
+        workflow_id: Option<&str>,
+    ) -> WorkflowServiceResult<Vec<ApprovalTask>>;
+    async fn list_requested(
+        &self,
+        tenant_id: &str,
+        user_id: &str,
+        status: ApprovalStatus,
+        workflow_id: Option<&str>,
+    ) -> WorkflowServiceResult<Vec<ApprovalTask>>;
+    async fn record_decision(
+        &self,
+        tenant_id: &str,
+        approval_id: &str,
+        status: ApprovalStatus,
+        decided_by: &str,
+        comment: Option<&str>,
+    ) -> WorkflowServiceResult<Option<ApprovalTask>>;
+    async fn escalate_approval(
+        &self,
+        tenant_id: &str,
+        approval_id: &str,
+        level: u32,
+        assignees: &Assignees,
+    ) -> WorkflowServiceResult<Option<ApprovalTask>>;
+    async fn expire_approval(&self, tenant_id: &str, approval_id: &str) -> WorkflowServiceResult<Option<ApprovalTask>>;
+}
+
+/// Dead letters are resolved once: every change applies only while the
+/// entry is still pending and reports whether it did
+#[async_trait]
+pub trait WorkflowDeadLetterRepository: Send + Sync {
+    /// Returns the entry and whether it was created; a workflow that was
+    /// captured before is returned unchanged
+    async fn capture(&self, request: &CaptureDeadLetterRequest) -> WorkflowServiceResult<(DeadLetter, bool)>;
+    async fn get(&self, tenant_id: &str, dead_letter_id: &str) -> WorkflowServiceResult<Option<DeadLetter>>;
+    /// Newest failures first, with the total count matching the filter
+    async fn list(
+        &self,
+        tenant_id: &str,
+        status: Option<DeadLetterStatus>,
+        workflow_type: Option<&str>,
+        limit: u32,
+        offset: u32,
+    ) -> WorkflowServiceResult<(Vec<DeadLetter>, u64)>;
+    async fn oldest_pending(&self, tenant_id: &str, workflow_type: &str, limit: u32) -> WorkflowServiceResult<Vec<String>>;
+    async fn count_pending(&self, tenant_id: &str, workflow_type: &str) -> WorkflowServiceResult<u32>;
+    async fn update_input(
+        &self,
+        tenant_id: &str,
+        dead_letter_id: &str,
+        input: &serde_json::Value,
+        edited_by: &str,
+    ) -> WorkflowServiceResult<Option<DeadLetter>>;
+    async fn mark_resubmitted(
+        &self,
+        tenant_id: &str,
+        dead_letter_id: &str,
+        workflow_id: &str,
+        resubmitted_by: &str,
+    ) -> WorkflowServiceResult<Option<DeadLetter>>;
+    /// Undo `mark_resubmitted` when the new run could not be started
+    async fn release_resubmission(&self, tenant_id: &str, dead_letter_id: &str, workflow_id: &str) -> WorkflowServiceResult<()>;
+    async fn discard(
+        &self,
+        tenant_id: &str,
+        dead_letter_id: &str,
+        discarded_by: &str,
+        note: Option<&str>,
+    ) -> WorkflowServiceResult<Option<DeadLetter>>;
+}
+
+#[async_trait]
+pub trait WorkflowTaskQueueRepository: Send + Sync {
+    async fn get_assignment(&self, tenant_id: &str) -> WorkflowServiceResult<Option<TaskQueueAssignment>>;
+    /// Replaces any earlier assignment; a dedicated queue belongs to one tenant
+    async fn set_assignment(&self, tenant_id: &str, assignment: &TaskQueueAssignment) -> WorkflowServiceResult<()>;
+    /// Returns whether there was an assignment to remove
+    async fn clear_assignment(&self, tenant_id: &str) -> WorkflowServiceResult<bool>;
+}
+
+/// Run costs are recorded once per workflow id; recording a run again
+/// replaces its earlier record
+#[async_trait]
+pub trait WorkflowCostRepository: Send + Sync {
+    async fn record_run(&self, cost: &RunCost) -> WorkflowServiceResult<()>;
+    async fn get_run(&self, tenant_id: &str, workflow_id: &str) -> WorkflowServiceResult<Option<RunCost>>;
+    /// Totals of the runs recorded in `[from, to)`, one entry per workflow type
+    async fn costs_by_workflow_type(
+        &self,
+        tenant_id: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> WorkflowServiceResult<Vec<WorkflowTypeCost>>;
+    async fn spend_since(&self, tenant_id: &str, since: DateTime<Utc>) -> WorkflowServiceResult<f64>;
+    async fn get_budget(&self, tenant_id: &str) -> WorkflowServiceResult<Option<WorkflowBudget>>;
+    async fn set_budget(&self, tenant_id: &str, budget: &WorkflowBudget) -> WorkflowServiceResult<()>;
+}
+
+/// A batch's counts are aggregated from its children's rows when it is read
+#[async_trait]
+pub trait WorkflowBatchRepository: Send + Sync {
+    /// Children are `(workflow_id, input)` in input order
+    async fn create_batch(&self, batch: &WorkflowBatch, children: &[(String, serde_json::Value)]) -> WorkflowServiceResult<()>;
+    async fn get_batch(&self, tenant_id: &str, batch_id: &str) -> WorkflowServiceResult<Option<WorkflowBatch>>;
+    /// Children in input order, with the total count matching the filter
+    async fn list_children(
+        &self,
+        tenant_id: &str,
+        batch_id: &str,
+        status: Option<BatchChildStatus>,
+        limit: u32,
+        offset: u32,
+    ) -> WorkflowServiceResult<(Vec<BatchChild>, u64)>;
+    /// Mark a pending child running unless the batch has been cancelled;
+    /// returns whether it was started
+    async fn start_child(&self, tenant_id: &str, batch_id: &str, index: u32) -> WorkflowServiceResult<bool>;
+    async fn finish_child(
+        &self,
+        tenant_id: &str,
+        batch_id: &str,
+        index: u32,
+        status: BatchChildStatus,
+        output: Option<&serde_json::Value>,
+        error: Option<&str>,
+    ) -> WorkflowServiceResult<()>;
+    /// Returns whether a running batch was found
+    async fn request_cancel(&self, tenant_id: &str, batch_id: &str) -> WorkflowServiceResult<bool>;
+    async fn skip_pending_children(&self, tenant_id: &str, batch_id: &str) -> WorkflowServiceResult<()>;
+    async fn finish_batch(&self, tenant_id: &str, batch_id: &str, status: BatchStatus) -> WorkflowServiceResult<()>;
+}
+
+/// Versions are shared by every tenant, so these queries aren't scoped to
+/// one; the execution queries read and update runs of all tenants
+#[async_trait]
+pub trait WorkflowVersionRepository: Send + Sync {
+    async fn list_versions(&self, workflow_type: &str) -> WorkflowServiceResult<Vec<WorkflowVersionRecord>>;
+    async fn get_version(&self, workflow_type: &str, version: &str) -> WorkflowServiceResult<Option<WorkflowVersionRecord>>;
+    async fn create_version(&self, version: &WorkflowVersionRecord) -> WorkflowServiceResult<()>;
+    /// Make the version the one new runs start on
+    async fn set_default(&self, workflow_type: &str, version: &str) -> WorkflowServiceResult<()>;
+    async fn set_status(&self, workflow_type: &str, version: &str, status: VersionStatus) -> WorkflowServiceResult<()>;
+    async fn deprecate(
+        &self,
+        workflow_type: &str,
+        version: &str,
+        reason: &str,
+        sunset_date: Option<DateTime<Utc>>,
+        migration_deadline: Option<DateTime<Utc>>,
+    ) -> WorkflowServiceResult<()>;
+    /// Running executions of the type per version
+    async fn running_counts(&self, workflow_type: &str) -> WorkflowServiceResult<HashMap<String, u32>>;
+    /// Running executions on the version, oldest first
+    async fn running_executions(
+        &self,
+        workflow_type: &str,
+        version: &str,
+        workflow_ids: Option<&[String]>,
+        limit: u32,
+    ) -> WorkflowServiceResult<Vec<VersionedExecution>>;
+    async fn close_execution(
+        &self,
+        workflow_id: &str,
+        run_id: &str,
+        status: &str,
+        closed_at: DateTime<Utc>,
+    ) -> WorkflowServiceResult<()>;
+    /// Move running executions between versions; returns the workflow ids moved
+    async fn retag_executions(
+        &self,
+        workflow_type: &str,
+        from_version: &str,
+        to_version: &str,
+        workflow_ids: &[String],
+    ) -> WorkflowServiceResult<Vec<String>>;
+    async fn create_rollout(&self, rollout: &VersionRollout) -> WorkflowServiceResult<()>;
+    async fn get_rollout(&self, migration_id: &str) -> WorkflowServiceResult<Option<VersionRollout>>;
+    async fn update_rollout(&self, rollout: &VersionRollout) -> WorkflowServiceResult<()>;
+}
+
+/// SLA breaches are recorded at most once per overrunning run, and a type
+/// has at most one open failure-rate breach
+#[async_trait]
+pub trait WorkflowSlaRepository: Send + Sync {
+    async fn list_slas(&self, tenant_id: &str) -> WorkflowServiceResult<Vec<WorkflowSla>>;
+    async fn get_sla(&self, tenant_id: &str, workflow_type: &str) -> WorkflowServiceResult<Option<WorkflowSla>>;
+    async fn set_sla(&self, sla: &WorkflowSla) -> WorkflowServiceResult<()>;
+    async fn delete_sla(&self, tenant_id: &str, workflow_type: &str) -> WorkflowServiceResult<bool>;
+    /// Runs of the type that finished since `since`, inline and Temporal alike
+    async fn window_stats(&self, tenant_id: &str, workflow_type: &str, since: DateTime<Utc>) -> WorkflowServiceResult<SlaWindowStats>;
+    /// Executions still recorded as running that started before `started_before`
+    async fn running_overruns(
+        &self,
+        tenant_id: &str,
+        workflow_type: &str,
+        started_before: DateTime<Utc>,
+    ) -> WorkflowServiceResult<Vec<RunningExecution>>;
+    /// Runs that finished since `since` after taking longer than `max_seconds`
+    async fn closed_overruns(
+        &self,
+        tenant_id: &str,
+        workflow_type: &str,
+        since: DateTime<Utc>,
+        max_seconds: i64,
+    ) -> WorkflowServiceResult<Vec<ClosedOverrun>>;
+    async fn close_execution(
+        &self,
+        tenant_id: &str,
+        workflow_id: &str,
+        run_id: &str,
+        status: &str,
+        closed_at: DateTime<Utc>,
+    ) -> WorkflowServiceResult<()>;
+    /// Returns whether the breach was new
+    async fn record_breach(&self, breach: &SlaBreach) -> WorkflowServiceResult<bool>;
+    /// Resolve the open duration breaches of runs that are no longer running
+    async fn resolve_finished_overruns(&self, tenant_id: &str, workflow_type: &str, at: DateTime<Utc>) -> WorkflowServiceResult<()>;
+    /// Resolve the open failure-rate breach, returning it if there was one
+    async fn resolve_failure_rate(&self, tenant_id: &str, workflow_type: &str, at: DateTime<Utc>) -> WorkflowServiceResult<Option<SlaBreach>>;
+    async fn record_notification(&self, tenant_id: &str, breach_id: &str, error: Option<&str>) -> WorkflowServiceResult<()>;
+    /// Newest breaches first, with the total count matching the filter
+    async fn list_breaches(
+        &self,
+        tenant_id: &str,
+        status: Option<SlaBreachStatus>,
+        workflow_type: Option<&str>,
+        limit: u32,
+        offset: u32,
+    ) -> WorkflowServiceResult<(Vec<SlaBreach>, u64)>;
+}
+
+/// Control actions are only ever appended
+#[async_trait]
+pub trait WorkflowControlRepository: Send + Sync {
+    async fn record_action(&self, record: &WorkflowControlRecord) -> WorkflowServiceResult<()>;
+    /// Newest first
+    async fn list_actions(&self, tenant_id: &str, workflow_id: &str) -> WorkflowServiceResult<Vec<WorkflowControlRecord>>;
+    /// The latest pause or resume requested for the workflow
+    async fn last_pause_action(&self, tenant_id: &str, workflow_id: &str) -> WorkflowServiceResult<Option<ControlAction>>;
+    /// The user who started the workflow's latest recorded execution
+    async fn execution_owner(&self, tenant_id: &str, workflow_id: &str) -> WorkflowServiceResult<Option<String>>;
+}
+
+/// Events are dispatched at least once. A trigger's firing for an event is
+/// claimed before its workflow starts, so it starts at most one run per event.
+#[async_trait]
+pub trait WorkflowTriggerRepository: Send + Sync {
+    async fn create_trigger(&self, trigger: &WorkflowTrigger) -> WorkflowServiceResult<()>;
+    async fn get_trigger(&self, tenant_id: &str, trigger_id: &str) -> WorkflowServiceResult<Option<WorkflowTrigger>>;
+    async fn list_triggers(&self, tenant_id: &str) -> WorkflowServiceResult<Vec<WorkflowTrigger>>;
+    async fn enabled_triggers(&self, tenant_id: &str) -> WorkflowServiceResult<Vec<WorkflowTrigger>>;
+    async fn count_triggers(&self, tenant_id: &str) -> WorkflowServiceResult<u32>;
+    async fn update_trigger(&self, trigger: &WorkflowTrigger) -> WorkflowServiceResult<()>;
+    async fn delete_trigger(&self, tenant_id: &str, trigger_id: &str) -> WorkflowServiceResult<bool>;
+
+    /// Store the event and queue it for dispatch. Returns false, queueing
+    /// nothing, when an event with the same id was received before.
+    async fn record_event(&self, event: &TriggerEvent) -> WorkflowServiceResult<bool>;
+    async fn get_event(&self, tenant_id: &str, event_id: &str) -> WorkflowServiceResult<Option<TriggerEvent>>;
+    /// Claim up to `limit` queued events of any tenant, oldest first, and
+    /// hide them from other dispatchers for `lease_seconds`
+    async fn claim_events(&self, limit: u32, lease_seconds: u64) -> WorkflowServiceResult<Vec<QueuedEvent>>;
+    /// Keep the event queued, to be claimed again after `delay_seconds`
+    async fn retry_event(&self, queued: &QueuedEvent, delay_seconds: u64, error: &str) -> WorkflowServiceResult<()>;
+    /// Take the event off the queue with its final status
+    async fn finish_event(&self, queued: &QueuedEvent, status: TriggerEventStatus, error: Option<&str>) -> WorkflowServiceResult<()>;
+
+    /// Returns false when the trigger already fired for the event
+    async fn claim_firing(&self, firing: &TriggerFiring) -> WorkflowServiceResult<bool>;
+    /// Undo `claim_firing` when the workflow could not be started
+    async fn release_firing(&self, tenant_id: &str, firing_id: &str) -> WorkflowServiceResult<()>;
+    /// Newest first
+    async fn list_firings(&self, tenant_id: &str, trigger_id: &str, limit: u32) -> WorkflowServiceResult<Vec<TriggerFiring>>;
+    async fn event_firings(&self, tenant_id: &str, event_id: &str) -> WorkflowServiceResult<Vec<TriggerFiring>>;
+}
+
+/// Every query runs in a transaction scoped to one tenant, so the row level
+/// security policies on the workflow tables apply
+async fn begin_tenant(pool: &PgPool, tenant_id: &str) -> WorkflowServiceResult<Transaction<'static, Postgres>> {
+    let mut tx = pool.begin().await?;
+    sqlx::query("SELECT set_config($1, $2, true)")
+        .bind(TENANT_ID_SETTING)
+        .bind(require_id(tenant_id, "tenant")?.to_string())
+        .execute(&mut *tx)
+        .await?;
+    Ok(tx)
+}
+
+fn require_id(id: &str, what: &str) -> WorkflowServiceResult<Uuid> {
+    Uuid::parse_str(id).map_err(|_| WorkflowServiceError::Validation(format!("Invalid {} id: {}", what, id)))
+}
+
+/// Names are unique per tenant; report a clash as a validation error
+fn name_taken(error: sqlx::Error, what: &str, name: &str) -> WorkflowServiceError {
+    match &error {
+        sqlx::Error::Database(db) if db.code().as_deref() == Some("23505") => {
+            WorkflowServiceError::Validation(format!("A {} named '{}' already exists", what, name))
+        }
+        _ => error.into(),
+    }
+}
+
+fn optional_id(id: Option<&str>, what: &str) -> WorkflowServiceResult<Option<Uuid>> {
+    id.map(|id| require_id(id, what)).transpose()
+}
+
+const SCHEDULE_COLUMNS: &str = "schedule_id, tenant_id, name, description, workflow_type, task_queue, cron_expression, \
+     timezone, jitter_seconds, calendar_id, input_data, is_enabled, paused_reason, paused_at, next_run_at, \
+     last_run_at, created_by, created_at, updated_at";
+
+fn schedule_from_row(row: &PgRow) -> WorkflowServiceResult<WorkflowSchedule> {
+    let tenant_id: Uuid = row.try_get("tenant_id")?;
+    let calendar_id: Option<Uuid> = row.try_get("calendar_id")?;
+    let created_by: Uuid = row.try_get("created_by")?;
+    let jitter_seconds: i32 = row.try_get("jitter_seconds")?;
+    let is_enabled: bool = row.try_get("is_enabled")?;
+    let name: Option<String> = row.try_get("name")?;
+    let schedule_id: String = row.try_get("schedule_id")?;
+
+    Ok(WorkflowSchedule {
+        // Schedules from before names were required are listed under their id
+        name: name.unwrap_or_else(|| schedule_id.clone()),
+        schedule_id,
+        tenant_id: tenant_id.to_string(),
+        description: row.try_get("description")?,
+        workflow_type: row.try_get("workflow_type")?,
+        task_queue: row.try_get("task_queue")?,
+        cron_expression: row.try_get("cron_expression")?,
+        timezone: row.try_get("timezone")?,
+        jitter_seconds: jitter_seconds.max(0) as u32,
+        calendar_id: calendar_id.map(|id| id.to_string()),
+        input: row.try_get::<Option<serde_json::Value>, _>("input_data")?.unwrap_or_default(),
+        state: if is_enabled { ScheduleState::Active } else { ScheduleState::Paused },
+        paused_reason: row.try_get("paused_reason")?,
+        paused_at: row.try_get("paused_at")?,
+        next_run_at: row.try_get("next_run_at")?,
+        last_run_at: row.try_get("last_run_at")?,
+        created_by: created_by.to_string(),
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+const CALENDAR_COLUMNS: &str = "id, tenant_id, name, description, timezone, excluded_dates, created_at, updated_at";
+
+fn calendar_from_row(row: &PgRow) -> WorkflowServiceResult<WorkflowCalendar> {
+    let id: Uuid = row.try_get("id")?;
+    let tenant_id: Uuid = row.try_get("tenant_id")?;
+    let excluded_dates: Vec<NaiveDate> = row.try_get("excluded_dates")?;
+
+    Ok(WorkflowCalendar {
+        calendar_id: id.to_string(),
+        tenant_id: tenant_id.to_string(),
+        name: row.try_get("name")?,
+        description: row.try_get("description")?,
+        timezone: row.try_get("timezone")?,
+        excluded_dates,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+pub struct PostgresWorkflowScheduleRepository {
+    pool: PgPool,
+}
+
+impl PostgresWorkflowScheduleRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WorkflowScheduleRepository for PostgresWorkflowScheduleRepository {
+    async fn create_schedule(&self, schedule: &WorkflowSchedule) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, &schedule.tenant_id).await?;
+
+        sqlx::query(
+            "INSERT INTO workflow_schedules (schedule_id, tenant_id, name, description, workflow_type, task_queue, \
+             cron_expression, timezone, jitter_seconds, calendar_id, input_data, is_enabled, paused_reason, paused_at, \
+             next_run_at, last_run_at, created_by, created_at, updated_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17, $18, $19)",
+        )
+        .bind(&schedule.schedule_id)
+        .bind(require_id(&schedule.tenant_id, "tenant")?)
+        .bind(&schedule.name)
+        .bind(&schedule.description)
+        .bind(&schedule.workflow_type)
+        .bind(&schedule.task_queue)
+        .bind(&schedule.cron_expression)
+        .bind(&schedule.timezone)
+        .bind(schedule.jitter_seconds as i32)
+        .bind(optional_id(schedule.calendar_id.as_deref(), "calendar")?)
+        .bind(&schedule.input)
+        .bind(schedule.state == ScheduleState::Active)
+        .bind(&schedule.paused_reason)
+        .bind(schedule.paused_at)
+        .bind(schedule.next_run_at)
+        .bind(schedule.last_run_at)
+        .bind(require_id(&schedule.created_by, "user")?)
+        .bind(schedule.created_at)
+        .bind(schedule.updated_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| name_taken(e, "schedule", &schedule.name))?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_schedule(&self, tenant_id: &str, schedule_id: &str) -> WorkflowServiceResult<Option<WorkflowSchedule>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM workflow_schedules WHERE schedule_id = $1",
+            SCHEDULE_COLUMNS
+        ))
+        .bind(schedule_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        row.as_ref().map(schedule_from_row).transpose()
+    }
+
+    async fn list_schedules(&self, tenant_id: &str) -> WorkflowServiceResult<Vec<WorkflowSchedule>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM workflow_schedules ORDER BY created_at",
+            SCHEDULE_COLUMNS
+        ))
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        rows.iter().map(schedule_from_row).collect()
+    }
+
+    async fn list_schedules_on_calendar(&self, tenant_id: &str, calendar_id: &str) -> WorkflowServiceResult<Vec<WorkflowSchedule>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM workflow_schedules WHERE calendar_id = $1 ORDER BY created_at",
+            SCHEDULE_COLUMNS
+        ))
+        .bind(require_id(calendar_id, "calendar")?)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        rows.iter().map(schedule_from_row).collect()
+    }
+
+    async fn count_schedules(&self, tenant_id: &str) -> WorkflowServiceResult<u32> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM workflow_schedules")
+            .fetch_one(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(count as u32)
+    }
+
+    async fn update_schedule(&self, schedule: &WorkflowSchedule) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, &schedule.tenant_id).await?;
+
+        let result = sqlx::query(
+            "UPDATE workflow_schedules SET description = $2, cron_expression = $3, timezone = $4, \
+             jitter_seconds = $5, calendar_id = $6, input_data = $7, is_enabled = $8, paused_reason = $9, \
+             paused_at = $10, next_run_at = $11, last_run_at = $12 \
+             WHERE schedule_id = $1",
+        )
+        .bind(&schedule.schedule_id)
+        .bind(&schedule.description)
+        .bind(&schedule.cron_expression)
+        .bind(&schedule.timezone)
+        .bind(schedule.jitter_seconds as i32)
+        .bind(optional_id(schedule.calendar_id.as_deref(), "calendar")?)
+        .bind(&schedule.input)
+        .bind(schedule.state == ScheduleState::Active)
+        .bind(&schedule.paused_reason)
+        .bind(schedule.paused_at)
+        .bind(schedule.next_run_at)
+        .bind(schedule.last_run_at)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(WorkflowServiceError::NotFound(format!("Schedule {}", schedule.schedule_id)));
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn delete_schedule(&self, tenant_id: &str, schedule_id: &str) -> WorkflowServiceResult<bool> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let result = sqlx::query("DELETE FROM workflow_schedules WHERE schedule_id = $1")
+            .bind(schedule_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn create_calendar(&self, calendar: &WorkflowCalendar) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, &calendar.tenant_id).await?;
+
+        sqlx::query(
+            "INSERT INTO workflow_calendars (id, tenant_id, name, description, timezone, excluded_dates, created_at, updated_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8)",
+        )
+        .bind(require_id(&calendar.calendar_id, "calendar")?)
+        .bind(require_id(&calendar.tenant_id, "tenant")?)
+        .bind(&calendar.name)
+        .bind(&calendar.description)
+        .bind(&calendar.timezone)
+        .bind(&calendar.excluded_dates)
+        .bind(calendar.created_at)
+        .bind(calendar.updated_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| name_taken(e, "calendar", &calendar.name))?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_calendar(&self, tenant_id: &str, calendar_id: &str) -> WorkflowServiceResult<Option<WorkflowCalendar>> {
+        let Ok(calendar_id) = Uuid::parse_str(calendar_id) else {
+            return Ok(None);
+        };
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let row = sqlx::query(&format!("SELECT {} FROM workflow_calendars WHERE id = $1", CALENDAR_COLUMNS))
+            .bind(calendar_id)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        row.as_ref().map(calendar_from_row).transpose()
+    }
+
+    async fn list_calendars(&self, tenant_id: &str) -> WorkflowServiceResult<Vec<WorkflowCalendar>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let rows = sqlx::query(&format!("SELECT {} FROM workflow_calendars ORDER BY name", CALENDAR_COLUMNS))
+            .fetch_all(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        rows.iter().map(calendar_from_row).collect()
+    }
+
+    async fn update_calendar(&self, calendar: &WorkflowCalendar) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, &calendar.tenant_id).await?;
+
+        let result = sqlx::query(
+            "UPDATE workflow_calendars SET description = $2, timezone = $3, excluded_dates = $4 WHERE id = $1",
+        )
+        .bind(require_id(&calendar.calendar_id, "calendar")?)
+        .bind(&calendar.description)
+        .bind(&calendar.timezone)
+        .bind(&calendar.excluded_dates)
+        .execute(&mut *tx)
+        .await?;
+
+        if result.rows_affected() == 0 {
+            return Err(WorkflowServiceError::NotFound(format!("Calendar {}", calendar.calendar_id)));
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn delete_calendar(&self, tenant_id: &str, calendar_id: &str) -> WorkflowServiceResult<bool> {
+        let Ok(calendar_id) = Uuid::parse_str(calendar_id) else {
+            return Ok(false);
+        };
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let result = sqlx::query("DELETE FROM workflow_calendars WHERE id = $1")
+            .bind(calendar_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn get_limits(&self, tenant_id: &str) -> WorkflowServiceResult<Option<ScheduleLimits>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let row = sqlx::query("SELECT max_schedules, min_interval_seconds FROM workflow_schedule_limits WHERE tenant_id = $1")
+            .bind(require_id(tenant_id, "tenant")?)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        row.map(|row| -> WorkflowServiceResult<ScheduleLimits> {
+            let max_schedules: i32 = row.try_get("max_schedules")?;
+            let min_interval_seconds: i32 = row.try_get("min_interval_seconds")?;
+            Ok(ScheduleLimits {
+                max_schedules: max_schedules as u32,
+                min_interval_seconds: min_interval_seconds as u32,
+            })
+        })
+        .transpose()
+    }
+
+    async fn set_limits(&self, tenant_id: &str, limits: &ScheduleLimits) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        sqlx::query(
+            "INSERT INTO workflow_schedule_limits (tenant_id, max_schedules, min_interval_seconds) VALUES ($1, $2, $3) \
+             ON CONFLICT (tenant_id) DO UPDATE SET max_schedules = EXCLUDED.max_schedules, \
+             min_interval_seconds = EXCLUDED.min_interval_seconds",
+        )
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(limits.max_schedules as i32)
+        .bind(limits.min_interval_seconds as i32)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+const TEMPLATE_COLUMNS: &str = "id, tenant_id, template_name, description, category, tags, is_public, version, \
+     task_queue, definition, created_by, created_at, updated_at";
+
+fn template_from_row(row: &PgRow) -> WorkflowServiceResult<WorkflowTemplate> {
+    let id: Uuid = row.try_get("id")?;
+    let tenant_id: Uuid = row.try_get("tenant_id")?;
+    let created_by: Uuid = row.try_get("created_by")?;
+    let version: i32 = row.try_get("version")?;
+    let description: Option<String> = row.try_get("description")?;
+    let definition: serde_json::Value = row.try_get("definition")?;
+
+    Ok(WorkflowTemplate {
+        template_id: id.to_string(),
+        tenant_id: tenant_id.to_string(),
+        name: row.try_get("template_name")?,
+        description: description.unwrap_or_default(),
+        category: row.try_get("category")?,
+        tags: row.try_get("tags")?,
+        is_public: row.try_get("is_public")?,
+        version: version as u32,
+        task_queue: row.try_get("task_queue")?,
+        definition: serde_json::from_value(definition).map_err(|e| {
+            WorkflowServiceError::InvalidTemplate(format!("Stored definition of template {} is unreadable: {}", id, e))
+        })?,
+        created_by: created_by.to_string(),
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+/// Success rate in percent over finished runs; running ones are not counted
+fn success_rate(completed: i64, failed: i64) -> f64 {
+    if completed + failed == 0 {
+        0.0
+    } else {
+        completed as f64 * 100.0 / (completed + failed) as f64
+    }
+}
+
+const FAILED_STATUSES: &str = "('failed', 'cancelled', 'terminated', 'timed_out')";
+
+pub struct PostgresWorkflowTemplateRepository {
+    pool: PgPool,
+}
+
+impl PostgresWorkflowTemplateRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WorkflowTemplateRepository for PostgresWorkflowTemplateRepository {
+    async fn create_template(&self, template: &WorkflowTemplate) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, &template.tenant_id).await?;
+
+        sqlx::query(
+            "INSERT INTO workflow_templates (id, tenant_id, template_name, workflow_type, description, category, tags, \
+             input_schema, task_queue, is_public, version, definition, created_by, created_at, updated_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15)",
+        )
+        .bind(require_id(&template.template_id, "template")?)
+        .bind(require_id(&template.tenant_id, "tenant")?)
+        .bind(&template.name)
+        .bind(TEMPLATE_WORKFLOW_TYPE)
+        .bind(&template.description)
+        .bind(&template.category)
+        .bind(&template.tags)
+        .bind(template.definition.input_schema())
+        .bind(&template.task_queue)
+        .bind(template.is_public)
+        .bind(template.version as i32)
+        .bind(serde_json::to_value(&template.definition)?)
+        .bind(require_id(&template.created_by, "user")?)
+        .bind(template.created_at)
+        .bind(template.updated_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| name_taken(e, "template", &template.name))?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_template(&self, tenant_id: &str, template_id: &str) -> WorkflowServiceResult<Option<WorkflowTemplate>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM workflow_templates WHERE id = $1 AND (tenant_id = $2 OR is_public)",
+            TEMPLATE_COLUMNS
+        ))
+        .bind(require_id(template_id, "template")?)
+        .bind(require_id(tenant_id, "tenant")?)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        row.as_ref().map(template_from_row).transpose()
+    }
+
+    async fn list_templates(&self, tenant_id: &str, params: &GetTemplatesParams) -> WorkflowServiceResult<Vec<WorkflowTemplateSummary>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        // Rows created before the template DSL have no definition and are
+        // not listed
+        let rows = sqlx::query(&format!(
+            "SELECT t.id, t.tenant_id, t.template_name, t.description, t.category, t.tags, t.is_public, t.version, \
+             t.created_by, t.created_at, t.updated_at, \
+             COUNT(e.id) AS usage_count, \
+             COUNT(e.id) FILTER (WHERE e.status = 'completed') AS completed_count, \
+             COUNT(e.id) FILTER (WHERE e.status IN {}) AS failed_count \
+             FROM workflow_templates t \
+             LEFT JOIN workflow_executions e ON e.template_id = t.id \
+             WHERE (t.tenant_id = $1 OR t.is_public) AND t.definition ? 'steps' \
+             AND ($2::TEXT IS NULL OR t.category = $2) \
+             AND ($3::TEXT IS NULL OR $3 = ANY(t.tags)) \
+             AND ($4::TEXT IS NULL OR t.template_name ILIKE '%' || $4 || '%' OR t.description ILIKE '%' || $4 || '%') \
+             GROUP BY t.id \
+             ORDER BY t.template_name",
+            FAILED_STATUSES
+        ))
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(&params.category)
+        .bind(&params.tag)
+        .bind(&params.search)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        rows.iter()
+            .map(|row| -> WorkflowServiceResult<WorkflowTemplateSummary> {
+                let id: Uuid = row.try_get("id")?;
+                let tenant_id: Uuid = row.try_get("tenant_id")?;
+                let created_by: Uuid = row.try_get("created_by")?;
+                let version: i32 = row.try_get("version")?;
+                let description: Option<String> = row.try_get("description")?;
+                let usage_count: i64 = row.try_get("usage_count")?;
+
+                Ok(WorkflowTemplateSummary {
+                    template_id: id.to_string(),
+                    tenant_id: tenant_id.to_string(),
+                    name: row.try_get("template_name")?,
+                    description: description.unwrap_or_default(),
+                    category: row.try_get("category")?,
+                    tags: row.try_get("tags")?,
+                    is_public: row.try_get("is_public")?,
+                    version: version as u32,
+                    created_by: created_by.to_string(),
+                    created_at: row.try_get("created_at")?,
+                    updated_at: row.try_get("updated_at")?,
+                    usage_count: usage_count as u32,
+                    success_rate: success_rate(row.try_get("completed_count")?, row.try_get("failed_count")?),
+                })
+            })
+            .collect()
+    }
+
+    async fn update_template(&self, template: &WorkflowTemplate) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, &template.tenant_id).await?;
+
+        // The row level security policy also admits public templates, so
+        // ownership is checked explicitly
+        let result = sqlx::query(
+            "UPDATE workflow_templates SET template_name = $3, description = $4, category = $5, tags = $6, \
+             input_schema = $7, is_public = $8, version = $9, definition = $10 \
+             WHERE id = $1 AND tenant_id = $2",
+        )
+        .bind(require_id(&template.template_id, "template")?)
+        .bind(require_id(&template.tenant_id, "tenant")?)
+        .bind(&template.name)
+        .bind(&template.description)
+        .bind(&template.category)
+        .bind(&template.tags)
+        .bind(template.definition.input_schema())
+        .bind(template.is_public)
+        .bind(template.version as i32)
+        .bind(serde_json::to_value(&template.definition)?)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| name_taken(e, "template", &template.name))?;
+
+        if result.rows_affected() == 0 {
+            return Err(WorkflowServiceError::NotFound(format!("Template {}", template.template_id)));
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn delete_template(&self, tenant_id: &str, template_id: &str) -> WorkflowServiceResult<bool> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let result = sqlx::query("DELETE FROM workflow_templates WHERE id = $1 AND tenant_id = $2")
+            .bind(require_id(template_id, "template")?)
+            .bind(require_id(tenant_id, "tenant")?)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn record_instance(&self, instance: &TemplateInstance) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, &instance.tenant_id).await?;
+
+        sqlx::query(
+            "INSERT INTO workflow_executions (tenant_id, user_id, workflow_id, workflow_type, run_id, task_queue, \
+             status, input_data, template_id, template_version, started_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, 'running', $7, $8, $9, $10)",
+        )
+        .bind(require_id(&instance.tenant_id, "tenant")?)
+        .bind(optional_id(instance.user_id.as_deref(), "user")?)
+        .bind(&instance.workflow_id)
+        .bind(TEMPLATE_WORKFLOW_TYPE)
+        .bind(&instance.run_id)
+        .bind(&instance.task_queue)
+        .bind(&instance.parameters)
+        .bind(require_id(&instance.template_id, "template")?)
+        .bind(instance.template_version as i32)
+        .bind(instance.started_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn active_workflows(&self, tenant_id: &str, template_id: &str) -> WorkflowServiceResult<Vec<String>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let workflow_ids: Vec<String> = sqlx::query_scalar(
+            "SELECT workflow_id FROM workflow_executions WHERE template_id = $1 AND status = 'running' ORDER BY started_at",
+        )
+        .bind(require_id(template_id, "template")?)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(workflow_ids)
+    }
+
+    async fn template_usage(&self, tenant_id: &str, template_id: &str) -> WorkflowServiceResult<TemplateUsageStats> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+        let template_id = require_id(template_id, "template")?;
+
+        let totals = sqlx::query(&format!(
+            "SELECT COUNT(*) AS total_uses, \
+             COUNT(*) FILTER (WHERE status = 'running') AS active_workflows, \
+             COUNT(*) FILTER (WHERE status = 'completed') AS completed_count, \
+             COUNT(*) FILTER (WHERE status IN {}) AS failed_count, \
+             (AVG(EXTRACT(EPOCH FROM completed_at - started_at)) FILTER (WHERE status = 'completed'))::FLOAT8 \
+             AS average_seconds, \
+             MAX(started_at) AS last_used \
+             FROM workflow_executions WHERE template_id = $1",
+            FAILED_STATUSES
+        ))
+        .bind(template_id)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        let trends = sqlx::query(
+            "SELECT date_trunc('day', started_at) AS day, COUNT(*) AS usage_count \
+             FROM workflow_executions \
+             WHERE template_id = $1 AND started_at >= NOW() - INTERVAL '30 days' \
+             GROUP BY 1 ORDER BY 1",
+        )
+        .bind(template_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+
+        let total_uses: i64 = totals.try_get("total_uses")?;
+        let active_workflows: i64 = totals.try_get("active_workflows")?;
+        let average_seconds: Option<f64> = totals.try_get("average_seconds")?;
+
+        Ok(TemplateUsageStats {
+            total_uses: total_uses as u32,
+            active_workflows: active_workflows as u32,
+            success_rate: success_rate(totals.try_get("completed_count")?, totals.try_get("failed_count")?),
+            average_duration_seconds: average_seconds.unwrap_or_default(),
+            usage_trends: trends
+                .iter()
+                .map(|row| -> WorkflowServiceResult<UsageTrend> {
+                    let usage_count: i64 = row.try_get("usage_count")?;
+                    Ok(UsageTrend {
+                        date: row.try_get("day")?,
+                        usage_count: usage_count as u32,
+                    })
+                })
+                .collect::<WorkflowServiceResult<_>>()?,
+            last_used: totals.try_get("last_used")?,
+        })
+    }
+}
+
+const APPROVAL_COLUMNS: &str = "id, tenant_id, workflow_id, step_id, title, description, assignee_users, assignee_roles, \
+     requested_by, allow_self_approval, context, status, escalation_level, escalated_at, due_at, decided_by, \
+     decision_comment, decided_at, created_at, updated_at";
+
+fn approval_from_row(row: &PgRow) -> WorkflowServiceResult<ApprovalTask> {
+    let id: Uuid = row.try_get("id")?;
+    let tenant_id: Uuid = row.try_get("tenant_id")?;
+    let assignee_users: Vec<Uuid> = row.try_get("assignee_users")?;
+    let requested_by: Option<Uuid> = row.try_get("requested_by")?;
+    let decided_by: Option<Uuid> = row.try_get("decided_by")?;
+    let status: String = row.try_get("status")?;
+    let escalation_level: i32 = row.try_get("escalation_level")?;
+
+    Ok(ApprovalTask {
+        approval_id: id.to_string(),
+        tenant_id: tenant_id.to_string(),
+        workflow_id: row.try_get("workflow_id")?,
+        step_id: row.try_get("step_id")?,
+        title: row.try_get("title")?,
+        description: row.try_get("description")?,
+        assignees: Assignees {
+            users: assignee_users.iter().map(Uuid::to_string).collect(),
+            roles: row.try_get("assignee_roles")?,
+        },
+        requested_by: requested_by.map(|id| id.to_string()),
+        allow_self_approval: row.try_get("allow_self_approval")?,
+        context: row.try_get("context")?,
+        status: ApprovalStatus::parse(&status)?,
+        escalation_level: escalation_level.max(0) as u32,
+        escalated_at: row.try_get("escalated_at")?,
+        due_at: row.try_get("due_at")?,
+        decided_by: decided_by.map(|id| id.to_string()),
+        comment: row.try_get("decision_comment")?,
+        decided_at: row.try_get("decided_at")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+fn user_ids(users: &[String]) -> WorkflowServiceResult<Vec<Uuid>> {
+    users.iter().map(|user| require_id(user, "assignee user")).collect()
+}
+
+pub struct PostgresWorkflowApprovalRepository {
+    pool: PgPool,
+}
+
+impl PostgresWorkflowApprovalRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WorkflowApprovalRepository for PostgresWorkflowApprovalRepository {
+    async fn create_approval(&self, request: &CreateApprovalTaskRequest) -> WorkflowServiceResult<(ApprovalTask, bool)> {
+        let mut tx = begin_tenant(&self.pool, &request.tenant_id).await?;
+        let tenant_id = require_id(&request.tenant_id, "tenant")?;
+        let policy = &request.policy;
+        let due_at = chrono::Utc::now() + chrono::Duration::seconds(policy.timeout_seconds as i64);
+
+        let created = sqlx::query(&format!(
+            "INSERT INTO workflow_approvals (tenant_id, workflow_id, step_id, title, description, assignee_users, \
+             assignee_roles, requested_by, allow_self_approval, context, due_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11) \
+             ON CONFLICT (workflow_id, step_id) DO NOTHING \
+             RETURNING {}",
+            APPROVAL_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(&request.workflow_id)
+        .bind(&request.step_id)
+        .bind(&policy.title)
+        .bind(&policy.description)
+        .bind(user_ids(&policy.assignees.users)?)
+        .bind(&policy.assignees.roles)
+        .bind(optional_id(request.requested_by.as_deref(), "user")?)
+        .bind(policy.allow_self_approval)
+        .bind(&request.context)
+        .bind(due_at)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let (row, created) = match created {
+            Some(row) => (row, true),
+            None => (
+                sqlx::query(&format!(
+                    "SELECT {} FROM workflow_approvals WHERE workflow_id = $1 AND step_id = $2 AND tenant_id = $3",
+                    APPROVAL_COLUMNS
+                ))
+                .bind(&request.workflow_id)
+                .bind(&request.step_id)
+                .bind(tenant_id)
+                .fetch_one(&mut *tx)
+                .await?,
+                false,
+            ),
+        };
+
+        tx.commit().await?;
+        Ok((approval_from_row(&row)?, created))
+    }
+
+    async fn get_approval(&self, tenant_id: &str, approval_id: &str) -> WorkflowServiceResult<Option<ApprovalTask>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let row = sqlx::query(&format!("SELECT {} FROM workflow_approvals WHERE id = $1", APPROVAL_COLUMNS))
+            .bind(require_id(approval_id, "approval")?)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        row.as_ref().map(approval_from_row).transpose()
+    }
+
+    async fn list_assigned(
+        &self,
+        tenant_id: &str,
+        user_id: &str,
+        roles: &[String],
+        status: ApprovalStatus,
+        workflow_id: Option<&str>,
+    ) -> WorkflowServiceResult<Vec<ApprovalTask>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM workflow_approvals \
+             WHERE ($1 = ANY(assignee_users) OR assignee_roles && $2) AND status = $3 \
+             AND ($4::TEXT IS NULL OR workflow_id = $4) \
+             ORDER BY due_at, created_at",
+            APPROVAL_COLUMNS
+        ))
+        .bind(require_id(user_id, "user")?)
+        .bind(roles)
+        .bind(status.as_str())
+        .bind(workflow_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        rows.iter().map(approval_from_row).collect()
+    }
+
+    async fn list_requested(
+        &self,
+        tenant_id: &str,
+        user_id: &str,
+        status: ApprovalStatus,
+        workflow_id: Option<&str>,
+    ) -> WorkflowServiceResult<Vec<ApprovalTask>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM workflow_approvals \
+             WHERE requested_by = $1 AND status = $2 AND ($3::TEXT IS NULL OR workflow_id = $3) \
+             ORDER BY due_at, created_at",
+            APPROVAL_COLUMNS
+        ))
+        .bind(require_id(user_id, "user")?)
+        .bind(status.as_str())
+        .bind(workflow_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        rows.iter().map(approval_from_row).collect()
+    }
+
+    async fn record_decision(
+        &self,
+        tenant_id: &str,
+        approval_id: &str,
+        status: ApprovalStatus,
+        decided_by: &str,
+        comment: Option<&str>,
+    ) -> WorkflowServiceResult<Option<ApprovalTask>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+        let decided_by = require_id(decided_by, "user")?;
+
+        let row = sqlx::query(&format!(
+            "UPDATE workflow_approvals SET status = $3, decided_by = $4, decision_comment = $5, decided_at = NOW() \
+             WHERE id = $1 AND tenant_id = $2 AND status = 'pending' RETURNING {}",
+            APPROVAL_COLUMNS
+        ))
+        .bind(require_id(approval_id, "approval")?)
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(status.as_str())
+        .bind(decided_by)
+        .bind(comment)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let Some(row) = row else {
+            return Ok(None);
+        };
+        let task = approval_from_row(&row)?;
+
+        // Keep the decision in the run's signal history when the run is tracked
+        sqlx::query(
+            "INSERT INTO workflow_signals (workflow_execution_id, tenant_id, signal_name, signal_data, sent_by) \
+             SELECT id, tenant_id, $3, $4, $5 FROM workflow_executions \
+             WHERE workflow_id = $1 AND tenant_id = $2 ORDER BY started_at DESC LIMIT 1",
+        )
+        .bind(&task.workflow_id)
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(crate::approvals::APPROVAL_SIGNAL)
+        .bind(serde_json::json!({
+            "approval_id": task.approval_id,
+            "step_id": task.step_id,
+            "status": status.as_str(),
+            "comment": comment,
+        }))
+        .bind(decided_by)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(task))
+    }
+
+    async fn escalate_approval(
+        &self,
+        tenant_id: &str,
+        approval_id: &str,
+        level: u32,
+        assignees: &Assignees,
+    ) -> WorkflowServiceResult<Option<ApprovalTask>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        // Levels only move forward, so a repeated escalation changes nothing
+        let row = sqlx::query(&format!(
+            "UPDATE workflow_approvals SET \
+             assignee_users = ARRAY(SELECT DISTINCT unnest(assignee_users || $4)), \
+             assignee_roles = ARRAY(SELECT DISTINCT unnest(assignee_roles || $5)), \
+             escalation_level = $3, escalated_at = NOW() \
+             WHERE id = $1 AND tenant_id = $2 AND status = 'pending' AND escalation_level < $3 \
+             RETURNING {}",
+            APPROVAL_COLUMNS
+        ))
+        .bind(require_id(approval_id, "approval")?)
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(level as i32)
+        .bind(user_ids(&assignees.users)?)
+        .bind(&assignees.roles)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        row.as_ref().map(approval_from_row).transpose()
+    }
+
+    async fn expire_approval(&self, tenant_id: &str, approval_id: &str) -> WorkflowServiceResult<Option<ApprovalTask>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let row = sqlx::query(&format!(
+            "UPDATE workflow_approvals SET status = 'expired' \
+             WHERE id = $1 AND tenant_id = $2 AND status = 'pending' RETURNING {}",
+            APPROVAL_COLUMNS
+        ))
+        .bind(require_id(approval_id, "approval")?)
+        .bind(require_id(tenant_id, "tenant")?)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        row.as_ref().map(approval_from_row).transpose()
+    }
+}
+
+const DEAD_LETTER_COLUMNS: &str = "id, tenant_id, workflow_id, run_id, workflow_type, task_queue, input_data, \
+     original_input_data, failure_message, failure_details, started_by, status, input_edited_by, input_edited_at, \
+     resubmit_count, resubmitted_workflow_id, resolved_by, resolution_note, resolved_at, failed_at, created_at, updated_at";
+
+fn dead_letter_from_row(row: &PgRow) -> WorkflowServiceResult<DeadLetter> {
+    let id: Uuid = row.try_get("id")?;
+    let tenant_id: Uuid = row.try_get("tenant_id")?;
+    let started_by: Option<Uuid> = row.try_get("started_by")?;
+    let input_edited_by: Option<Uuid> = row.try_get("input_edited_by")?;
+    let resolved_by: Option<Uuid> = row.try_get("resolved_by")?;
+    let status: String = row.try_get("status")?;
+    let resubmit_count: i32 = row.try_get("resubmit_count")?;
+
+    Ok(DeadLetter {
+        dead_letter_id: id.to_string(),
+        tenant_id: tenant_id.to_string(),
+        workflow_id: row.try_get("workflow_id")?,
+        run_id: row.try_get("run_id")?,
+        workflow_type: row.try_get("workflow_type")?,
+        task_queue: row.try_get("task_queue")?,
+        input: row.try_get("input_data")?,
+        original_input: row.try_get("original_input_data")?,
+        failure_message: row.try_get("failure_message")?,
+        failure_details: row.try_get("failure_details")?,
+        started_by: started_by.map(|id| id.to_string()),
+        status: DeadLetterStatus::parse(&status)?,
+        input_edited_by: input_edited_by.map(|id| id.to_string()),
+        input_edited_at: row.try_get("input_edited_at")?,
+        resubmit_count: resubmit_count.max(0) as u32,
+        resubmitted_workflow_id: row.try_get("resubmitted_workflow_id")?,
+        resolved_by: resolved_by.map(|id| id.to_string()),
+        resolution_note: row.try_get("resolution_note")?,
+        resolved_at: row.try_get("resolved_at")?,
+        failed_at: row.try_get("failed_at")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+pub struct PostgresWorkflowDeadLetterRepository {
+    pool: PgPool,
+}
+
+impl PostgresWorkflowDeadLetterRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WorkflowDeadLetterRepository for PostgresWorkflowDeadLetterRepository {
+    async fn capture(&self, request: &CaptureDeadLetterRequest) -> WorkflowServiceResult<(DeadLetter, bool)> {
+        let mut tx = begin_tenant(&self.pool, &request.tenant_id).await?;
+        let tenant_id = require_id(&request.tenant_id, "tenant")?;
+        let failure_details = if request.failure_details.is_null() {
+            serde_json::json!({})
+        } else {
+            request.failure_details.clone()
+        };
+
+        let created = sqlx::query(&format!(
+            "INSERT INTO workflow_dead_letters (tenant_id, workflow_id, run_id, workflow_type, task_queue, input_data, \
+             original_input_data, failure_message, failure_details, started_by) \
+             VALUES ($1, $2, $3, $4, $5, $6, $6, $7, $8, $9) \
+             ON CONFLICT (workflow_id) DO NOTHING \
+             RETURNING {}",
+            DEAD_LETTER_COLUMNS
+        ))
+        .bind(tenant_id)
+        .bind(&request.workflow_id)
+        .bind(&request.run_id)
+        .bind(&request.workflow_type)
+        .bind(&request.task_queue)
+        .bind(&request.input)
+        .bind(&request.failure_message)
+        .bind(failure_details)
+        .bind(optional_id(request.started_by.as_deref(), "user")?)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        let (row, created) = match created {
+            Some(row) => (row, true),
+            None => (
+                sqlx::query(&format!(
+                    "SELECT {} FROM workflow_dead_letters WHERE workflow_id = $1 AND tenant_id = $2",
+                    DEAD_LETTER_COLUMNS
+                ))
+                .bind(&request.workflow_id)
+                .bind(tenant_id)
+                .fetch_one(&mut *tx)
+                .await?,
+                false,
+            ),
+        };
+
+        tx.commit().await?;
+        Ok((dead_letter_from_row(&row)?, created))
+    }
+
+    async fn get(&self, tenant_id: &str, dead_letter_id: &str) -> WorkflowServiceResult<Option<DeadLetter>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let row = sqlx::query(&format!("SELECT {} FROM workflow_dead_letters WHERE id = $1", DEAD_LETTER_COLUMNS))
+            .bind(require_id(dead_letter_id, "dead letter")?)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        row.as_ref().map(dead_letter_from_row).transpose()
+    }
+
+    async fn list(
+        &self,
+        tenant_id: &str,
+        status: Option<DeadLetterStatus>,
+        workflow_type: Option<&str>,
+        limit: u32,
+        offset: u32,
+    ) -> WorkflowServiceResult<(Vec<DeadLetter>, u64)> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+        let status = status.map(|s| s.as_str());
+        let filter = "($1::TEXT IS NULL OR status = $1) AND ($2::TEXT IS NULL OR workflow_type = $2)";
+
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM workflow_dead_letters WHERE {} ORDER BY failed_at DESC LIMIT $3 OFFSET $4",
+            DEAD_LETTER_COLUMNS, filter
+        ))
+        .bind(status)
+        .bind(workflow_type)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM workflow_dead_letters WHERE {}", filter))
+            .bind(status)
+            .bind(workflow_type)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        let dead_letters = rows.iter().map(dead_letter_from_row).collect::<WorkflowServiceResult<Vec<_>>>()?;
+        Ok((dead_letters, total.max(0) as u64))
+    }
+
+    async fn oldest_pending(&self, tenant_id: &str, workflow_type: &str, limit: u32) -> WorkflowServiceResult<Vec<String>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let ids: Vec<Uuid> = sqlx::query_scalar(
+            "SELECT id FROM workflow_dead_letters WHERE status = 'pending' AND workflow_type = $1 \
+             ORDER BY failed_at LIMIT $2",
+        )
+        .bind(workflow_type)
+        .bind(limit as i64)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(ids.iter().map(Uuid::to_string).collect())
+    }
+
+    async fn count_pending(&self, tenant_id: &str, workflow_type: &str) -> WorkflowServiceResult<u32> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let count: i64 = sqlx::query_scalar(
+            "SELECT COUNT(*) FROM workflow_dead_letters WHERE status = 'pending' AND workflow_type = $1",
+        )
+        .bind(workflow_type)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(count.max(0) as u32)
+    }
+
+    async fn update_input(
+        &self,
+        tenant_id: &str,
+        dead_letter_id: &str,
+        input: &serde_json::Value,
+        edited_by: &str,
+    ) -> WorkflowServiceResult<Option<DeadLetter>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let row = sqlx::query(&format!(
+            "UPDATE workflow_dead_letters SET input_data = $3, input_edited_by = $4, input_edited_at = NOW() \
+             WHERE id = $1 AND tenant_id = $2 AND status = 'pending' RETURNING {}",
+            DEAD_LETTER_COLUMNS
+        ))
+        .bind(require_id(dead_letter_id, "dead letter")?)
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(input)
+        .bind(require_id(edited_by, "user")?)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        row.as_ref().map(dead_letter_from_row).transpose()
+    }
+
+    async fn mark_resubmitted(
+        &self,
+        tenant_id: &str,
+        dead_letter_id: &str,
+        workflow_id: &str,
+        resubmitted_by: &str,
+    ) -> WorkflowServiceResult<Option<DeadLetter>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let row = sqlx::query(&format!(
+            "UPDATE workflow_dead_letters SET status = 'resubmitted', resubmitted_workflow_id = $3, \
+             resubmit_count = resubmit_count + 1, resolved_by = $4, resolved_at = NOW() \
+             WHERE id = $1 AND tenant_id = $2 AND status = 'pending' RETURNING {}",
+            DEAD_LETTER_COLUMNS
+        ))
+        .bind(require_id(dead_letter_id, "dead letter")?)
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(workflow_id)
+        .bind(require_id(resubmitted_by, "user")?)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        row.as_ref().map(dead_letter_from_row).transpose()
+    }
+
+    async fn release_resubmission(&self, tenant_id: &str, dead_letter_id: &str, workflow_id: &str) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        sqlx::query(
+            "UPDATE workflow_dead_letters SET status = 'pending', resubmitted_workflow_id = NULL, \
+             resubmit_count = resubmit_count - 1, resolved_by = NULL, resolved_at = NULL \
+             WHERE id = $1 AND tenant_id = $2 AND status = 'resubmitted' AND resubmitted_workflow_id = $3",
+        )
+        .bind(require_id(dead_letter_id, "dead letter")?)
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(workflow_id)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn discard(
+        &self,
+        tenant_id: &str,
+        dead_letter_id: &str,
+        discarded_by: &str,
+        note: Option<&str>,
+    ) -> WorkflowServiceResult<Option<DeadLetter>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let row = sqlx::query(&format!(
+            "UPDATE workflow_dead_letters SET status = 'discarded', resolved_by = $3, resolution_note = $4, \
+             resolved_at = NOW() \
+             WHERE id = $1 AND tenant_id = $2 AND status = 'pending' RETURNING {}",
+            DEAD_LETTER_COLUMNS
+        ))
+        .bind(require_id(dead_letter_id, "dead letter")?)
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(require_id(discarded_by, "user")?)
+        .bind(note)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        row.as_ref().map(dead_letter_from_row).transpose()
+    }
+}
+
+pub struct PostgresWorkflowTaskQueueRepository {
+    pool: PgPool,
+}
+
+impl PostgresWorkflowTaskQueueRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WorkflowTaskQueueRepository for PostgresWorkflowTaskQueueRepository {
+    async fn get_assignment(&self, tenant_id: &str) -> WorkflowServiceResult<Option<TaskQueueAssignment>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let row = sqlx::query("SELECT tier, dedicated_queue FROM workflow_task_queue_assignments WHERE tenant_id = $1")
+            .bind(require_id(tenant_id, "tenant")?)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        row.map(|row| -> WorkflowServiceResult<TaskQueueAssignment> {
+            Ok(TaskQueueAssignment {
+                tier: row.try_get("tier")?,
+                dedicated_queue: row.try_get("dedicated_queue")?,
+            })
+        })
+        .transpose()
+    }
+
+    async fn set_assignment(&self, tenant_id: &str, assignment: &TaskQueueAssignment) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        sqlx::query(
+            "INSERT INTO workflow_task_queue_assignments (tenant_id, tier, dedicated_queue) VALUES ($1, $2, $3) \
+             ON CONFLICT (tenant_id) DO UPDATE SET tier = EXCLUDED.tier, dedicated_queue = EXCLUDED.dedicated_queue",
+        )
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(&assignment.tier)
+        .bind(&assignment.dedicated_queue)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| match &e {
+            sqlx::Error::Database(db) if db.code().as_deref() == Some("23505") => WorkflowServiceError::Validation(
+                format!(
+                    "Task queue '{}' is already dedicated to another tenant",
+                    assignment.dedicated_queue.as_deref().unwrap_or_default()
+                ),
+            ),
+            _ => e.into(),
+        })?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn clear_assignment(&self, tenant_id: &str) -> WorkflowServiceResult<bool> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let result = sqlx::query("DELETE FROM workflow_task_queue_assignments WHERE tenant_id = $1")
+            .bind(require_id(tenant_id, "tenant")?)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+}
+
+const RUN_COST_COLUMNS: &str = "tenant_id, workflow_id, workflow_type, activity_count, activity_time_ms, retry_count, \
+     failed_calls, service_usage, compute_cost, api_cost, total_cost, started_at, succeeded, recorded_at";
+
+fn run_cost_from_row(row: &PgRow) -> WorkflowServiceResult<RunCost> {
+    let tenant_id: Uuid = row.try_get("tenant_id")?;
+    let activity_count: i32 = row.try_get("activity_count")?;
+    let activity_time_ms: i64 = row.try_get("activity_time_ms")?;
+    let retry_count: i32 = row.try_get("retry_count")?;
+    let failed_calls: i32 = row.try_get("failed_calls")?;
+    let service_usage: serde_json::Value = row.try_get("service_usage")?;
+
+    Ok(RunCost {
+        tenant_id: tenant_id.to_string(),
+        workflow_id: row.try_get("workflow_id")?,
+        workflow_type: row.try_get("workflow_type")?,
+        usage: RunUsage {
+            activity_count: activity_count as u32,
+            activity_time_ms: activity_time_ms as u64,
+            retry_count: retry_count as u32,
+            failed_calls: failed_calls as u32,
+            services: serde_json::from_value(service_usage)?,
+        },
+        compute_cost: row.try_get("compute_cost")?,
+        api_cost: row.try_get("api_cost")?,
+        total_cost: row.try_get("total_cost")?,
+        started_at: row.try_get("started_at")?,
+        succeeded: row.try_get("succeeded")?,
+        recorded_at: row.try_get("recorded_at")?,
+    })
+}
+
+pub struct PostgresWorkflowCostRepository {
+    pool: PgPool,
+}
+
+impl PostgresWorkflowCostRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WorkflowCostRepository for PostgresWorkflowCostRepository {
+    async fn record_run(&self, cost: &RunCost) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, &cost.tenant_id).await?;
+
+        sqlx::query(
+            "INSERT INTO workflow_run_costs (tenant_id, workflow_id, workflow_type, activity_count, activity_time_ms, \
+             retry_count, failed_calls, service_usage, compute_cost, api_cost, total_cost, started_at, succeeded, \
+             recorded_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14) \
+             ON CONFLICT (tenant_id, workflow_id) DO UPDATE SET workflow_type = EXCLUDED.workflow_type, \
+             activity_count = EXCLUDED.activity_count, activity_time_ms = EXCLUDED.activity_time_ms, \
+             retry_count = EXCLUDED.retry_count, failed_calls = EXCLUDED.failed_calls, \
+             service_usage = EXCLUDED.service_usage, compute_cost = EXCLUDED.compute_cost, \
+             api_cost = EXCLUDED.api_cost, total_cost = EXCLUDED.total_cost, started_at = EXCLUDED.started_at, \
+             succeeded = EXCLUDED.succeeded, recorded_at = EXCLUDED.recorded_at",
+        )
+        .bind(require_id(&cost.tenant_id, "tenant")?)
+        .bind(&cost.workflow_id)
+        .bind(&cost.workflow_type)
+        .bind(cost.usage.activity_count as i32)
+        .bind(cost.usage.activity_time_ms as i64)
+        .bind(cost.usage.retry_count as i32)
+        .bind(cost.usage.failed_calls as i32)
+        .bind(serde_json::to_value(&cost.usage.services)?)
+        .bind(cost.compute_cost)
+        .bind(cost.api_cost)
+        .bind(cost.total_cost)
+        .bind(cost.started_at)
+        .bind(cost.succeeded)
+        .bind(cost.recorded_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_run(&self, tenant_id: &str, workflow_id: &str) -> WorkflowServiceResult<Option<RunCost>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM workflow_run_costs WHERE tenant_id = $1 AND workflow_id = $2",
+            RUN_COST_COLUMNS
+        ))
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(workflow_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        row.as_ref().map(run_cost_from_row).transpose()
+    }
+
+    async fn costs_by_workflow_type(
+        &self,
+        tenant_id: &str,
+        from: DateTime<Utc>,
+        to: DateTime<Utc>,
+    ) -> WorkflowServiceResult<Vec<WorkflowTypeCost>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let rows = sqlx::query(
+            "SELECT workflow_type, COUNT(*) AS runs, SUM(activity_time_ms)::BIGINT AS activity_time_ms, \
+             SUM(retry_count)::BIGINT AS retry_count, SUM(activity_count)::BIGINT AS api_calls, \
+             SUM(total_cost) AS total_cost \
+             FROM workflow_run_costs WHERE tenant_id = $1 AND recorded_at >= $2 AND recorded_at < $3 \
+             GROUP BY workflow_type",
+        )
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(from)
+        .bind(to)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        rows.iter()
+            .map(|row| {
+                let runs: i64 = row.try_get("runs")?;
+                let activity_time_ms: i64 = row.try_get("activity_time_ms")?;
+                let retry_count: i64 = row.try_get("retry_count")?;
+                let api_calls: i64 = row.try_get("api_calls")?;
+                Ok(WorkflowTypeCost {
+                    workflow_type: row.try_get("workflow_type")?,
+                    runs: runs as u32,
+                    activity_time_ms: activity_time_ms as u64,
+                    retry_count: retry_count as u32,
+                    api_calls: api_calls as u32,
+                    total_cost: row.try_get("total_cost")?,
+                })
+            })
+            .collect()
+    }
+
+    async fn spend_since(&self, tenant_id: &str, since: DateTime<Utc>) -> WorkflowServiceResult<f64> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let spent: f64 = sqlx::query_scalar(
+            "SELECT COALESCE(SUM(total_cost), 0) FROM workflow_run_costs WHERE tenant_id = $1 AND recorded_at >= $2",
+        )
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(since)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(spent)
+    }
+
+    async fn get_budget(&self, tenant_id: &str) -> WorkflowServiceResult<Option<WorkflowBudget>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let row = sqlx::query("SELECT monthly_limit, warn_at_percent FROM workflow_budgets WHERE tenant_id = $1")
+            .bind(require_id(tenant_id, "tenant")?)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        row.map(|row| -> WorkflowServiceResult<WorkflowBudget> {
+            let warn_at_percent: i32 = row.try_get("warn_at_percent")?;
+            Ok(WorkflowBudget {
+                monthly_limit: row.try_get("monthly_limit")?,
+                warn_at_percent: warn_at_percent as u32,
+            })
+        })
+        .transpose()
+    }
+
+    async fn set_budget(&self, tenant_id: &str, budget: &WorkflowBudget) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        sqlx::query(
+            "INSERT INTO workflow_budgets (tenant_id, monthly_limit, warn_at_percent) VALUES ($1, $2, $3) \
+             ON CONFLICT (tenant_id) DO UPDATE SET monthly_limit = EXCLUDED.monthly_limit, \
+             warn_at_percent = EXCLUDED.warn_at_percent",
+        )
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(budget.monthly_limit)
+        .bind(budget.warn_at_percent as i32)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+const BATCH_COLUMNS: &str = "b.id, b.tenant_id, b.workflow_type, b.parallelism, b.fail_fast, b.status, b.cancel_requested, \
+     b.created_by, b.created_at, b.updated_at, b.completed_at, b.total_children, \
+     COUNT(*) FILTER (WHERE c.status = 'pending') AS pending_children, \
+     COUNT(*) FILTER (WHERE c.status = 'running') AS running_children, \
+     COUNT(*) FILTER (WHERE c.status = 'completed') AS completed_children, \
+     COUNT(*) FILTER (WHERE c.status = 'failed') AS failed_children, \
+     COUNT(*) FILTER (WHERE c.status = 'skipped') AS skipped_children";
+
+fn batch_from_row(row: &PgRow) -> WorkflowServiceResult<WorkflowBatch> {
+    let id: Uuid = row.try_get("id")?;
+    let tenant_id: Uuid = row.try_get("tenant_id")?;
+    let created_by: Uuid = row.try_get("created_by")?;
+    let status: String = row.try_get("status")?;
+    let parallelism: i32 = row.try_get("parallelism")?;
+    let count = |column: &str| -> WorkflowServiceResult<u32> {
+        let value: i64 = row.try_get(column)?;
+        Ok(value.max(0) as u32)
+    };
+    let total: i32 = row.try_get("total_children")?;
+
+    Ok(WorkflowBatch {
+        batch_id: id.to_string(),
+        tenant_id: tenant_id.to_string(),
+        workflow_type: row.try_get("workflow_type")?,
+        parallelism: parallelism.max(1) as u32,
+        fail_fast: row.try_get("fail_fast")?,
+        status: BatchStatus::parse(&status)?,
+        cancel_requested: row.try_get("cancel_requested")?,
+        counts: BatchCounts {
+            total: total.max(0) as u32,
+            pending: count("pending_children")?,
+            running: count("running_children")?,
+            completed: count("completed_children")?,
+            failed: count("failed_children")?,
+            skipped: count("skipped_children")?,
+        },
+        created_by: created_by.to_string(),
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+        completed_at: row.try_get("completed_at")?,
+    })
+}
+
+const BATCH_CHILD_COLUMNS: &str =
+    "child_index, workflow_id, status, input_data, output_data, error_message, started_at, completed_at";
+
+fn batch_child_from_row(row: &PgRow) -> WorkflowServiceResult<BatchChild> {
+    let index: i32 = row.try_get("child_index")?;
+    let status: String = row.try_get("status")?;
+
+    Ok(BatchChild {
+        index: index.max(0) as u32,
+        workflow_id: row.try_get("workflow_id")?,
+        status: BatchChildStatus::parse(&status)?,
+        input: row.try_get("input_data")?,
+        output: row.try_get("output_data")?,
+        error: row.try_get("error_message")?,
+        started_at: row.try_get("started_at")?,
+        completed_at: row.try_get("completed_at")?,
+    })
+}
+
+pub struct PostgresWorkflowBatchRepository {
+    pool: PgPool,
+}
+
+impl PostgresWorkflowBatchRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WorkflowBatchRepository for PostgresWorkflowBatchRepository {
+    async fn create_batch(&self, batch: &WorkflowBatch, children: &[(String, serde_json::Value)]) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, &batch.tenant_id).await?;
+        let batch_id = require_id(&batch.batch_id, "batch")?;
+        let tenant_id = require_id(&batch.tenant_id, "tenant")?;
+
+        sqlx::query(
+            "INSERT INTO workflow_batches (id, tenant_id, workflow_type, parallelism, fail_fast, total_children, \
+             status, created_by, created_at, updated_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        )
+        .bind(batch_id)
+        .bind(tenant_id)
+        .bind(&batch.workflow_type)
+        .bind(batch.parallelism as i32)
+        .bind(batch.fail_fast)
+        .bind(children.len() as i32)
+        .bind(batch.status.as_str())
+        .bind(require_id(&batch.created_by, "user")?)
+        .bind(batch.created_at)
+        .bind(batch.updated_at)
+        .execute(&mut *tx)
+        .await?;
+
+        for (index, (workflow_id, input)) in children.iter().enumerate() {
+            sqlx::query(
+                "INSERT INTO workflow_batch_children (batch_id, tenant_id, child_index, workflow_id, input_data) \
+                 VALUES ($1, $2, $3, $4, $5)",
+            )
+            .bind(batch_id)
+            .bind(tenant_id)
+            .bind(index as i32)
+            .bind(workflow_id)
+            .bind(input)
+            .execute(&mut *tx)
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_batch(&self, tenant_id: &str, batch_id: &str) -> WorkflowServiceResult<Option<WorkflowBatch>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM workflow_batches b LEFT JOIN workflow_batch_children c ON c.batch_id = b.id \
+             WHERE b.id = $1 GROUP BY b.id",
+            BATCH_COLUMNS
+        ))
+        .bind(require_id(batch_id, "batch")?)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        row.as_ref().map(batch_from_row).transpose()
+    }
+
+    async fn list_children(
+        &self,
+        tenant_id: &str,
+        batch_id: &str,
+        status: Option<BatchChildStatus>,
+        limit: u32,
+        offset: u32,
+    ) -> WorkflowServiceResult<(Vec<BatchChild>, u64)> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+        let batch_id = require_id(batch_id, "batch")?;
+        let status = status.map(|s| s.as_str());
+        let filter = "batch_id = $1 AND ($2::TEXT IS NULL OR status = $2)";
+
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM workflow_batch_children WHERE {} ORDER BY child_index LIMIT $3 OFFSET $4",
+            BATCH_CHILD_COLUMNS, filter
+        ))
+        .bind(batch_id)
+        .bind(status)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM workflow_batch_children WHERE {}", filter))
+            .bind(batch_id)
+            .bind(status)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        let children = rows.iter().map(batch_child_from_row).collect::<WorkflowServiceResult<Vec<_>>>()?;
+        Ok((children, total.max(0) as u64))
+    }
+
+    async fn start_child(&self, tenant_id: &str, batch_id: &str, index: u32) -> WorkflowServiceResult<bool> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let result = sqlx::query(
+            "UPDATE workflow_batch_children c SET status = 'running', started_at = NOW() \
+             FROM workflow_batches b WHERE b.id = c.batch_id AND c.batch_id = $1 AND c.child_index = $2 \
+             AND c.status = 'pending' AND NOT b.cancel_requested",
+        )
+        .bind(require_id(batch_id, "batch")?)
+        .bind(index as i32)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn finish_child(
+        &self,
+        tenant_id: &str,
+        batch_id: &str,
+        index: u32,
+        status: BatchChildStatus,
+        output: Option<&serde_json::Value>,
+        error: Option<&str>,
+    ) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+        let batch_id = require_id(batch_id, "batch")?;
+
+        sqlx::query(
+            "UPDATE workflow_batch_children SET status = $3, output_data = $4, error_message = $5, completed_at = NOW() \
+             WHERE batch_id = $1 AND child_index = $2",
+        )
+        .bind(batch_id)
+        .bind(index as i32)
+        .bind(status.as_str())
+        .bind(output)
+        .bind(error)
+        .execute(&mut *tx)
+        .await?;
+
+        // Touch the batch so its updated_at tracks progress
+        sqlx::query("UPDATE workflow_batches SET updated_at = NOW() WHERE id = $1")
+            .bind(batch_id)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn request_cancel(&self, tenant_id: &str, batch_id: &str) -> WorkflowServiceResult<bool> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let result = sqlx::query(
+            "UPDATE workflow_batches SET cancel_requested = true WHERE id = $1 AND status = 'running'",
+        )
+        .bind(require_id(batch_id, "batch")?)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn skip_pending_children(&self, tenant_id: &str, batch_id: &str) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        sqlx::query(
+            "UPDATE workflow_batch_children SET status = 'skipped', completed_at = NOW() \
+             WHERE batch_id = $1 AND status = 'pending'",
+        )
+        .bind(require_id(batch_id, "batch")?)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn finish_batch(&self, tenant_id: &str, batch_id: &str, status: BatchStatus) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        sqlx::query("UPDATE workflow_batches SET status = $2, completed_at = NOW() WHERE id = $1")
+            .bind(require_id(batch_id, "batch")?)
+            .bind(status.as_str())
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
+
+const VERSION_COLUMNS: &str = "workflow_type, version, status, is_default, description, input_schema, breaking_changes, \
+     migration_notes, registered_at, deprecated_at, deprecation_reason, sunset_date, migration_deadline";
+
+fn version_from_row(row: &PgRow) -> WorkflowServiceResult<WorkflowVersionRecord> {
+    let status: String = row.try_get("status")?;
+    let breaking_changes: serde_json::Value = row.try_get("breaking_changes")?;
+
+    Ok(WorkflowVersionRecord {
+        workflow_type: row.try_get("workflow_type")?,
+        version: row.try_get("version")?,
+        status: VersionStatus::parse(&status)?,
+        is_default: row.try_get("is_default")?,
+        description: row.try_get("description")?,
+        schema: row.try_get("input_schema")?,
+        breaking_changes: serde_json::from_value(breaking_changes)?,
+        migration_notes: row.try_get("migration_notes")?,
+        registered_at: row.try_get("registered_at")?,
+        deprecated_at: row.try_get("deprecated_at")?,
+        deprecation_reason: row.try_get("deprecation_reason")?,
+        sunset_date: row.try_get("sunset_date")?,
+        migration_deadline: row.try_get("migration_deadline")?,
+    })
+}
+
+const ROLLOUT_COLUMNS: &str = "id, workflow_type, from_version, to_version, strategy, status, dry_run, rollback_enabled, \
+     batch_size, total_workflows, migrated_workflow_ids, failed_migrations, remaining_workflows, errors, \
+     rollback_reason, started_at, updated_at, completed_at";
+
+fn rollout_from_row(row: &PgRow) -> WorkflowServiceResult<VersionRollout> {
+    let id: Uuid = row.try_get("id")?;
+    let strategy: String = row.try_get("strategy")?;
+    let status: String = row.try_get("status")?;
+    let count = |column: &str| -> WorkflowServiceResult<u32> {
+        let value: i32 = row.try_get(column)?;
+        Ok(value.max(0) as u32)
+    };
+    let migrated: serde_json::Value = row.try_get("migrated_workflow_ids")?;
+    let errors: serde_json::Value = row.try_get("errors")?;
+
+    Ok(VersionRollout {
+        migration_id: id.to_string(),
+        workflow_type: row.try_get("workflow_type")?,
+        from_version: row.try_get("from_version")?,
+        to_version: row.try_get("to_version")?,
+        strategy: RolloutStrategy::parse(&strategy)?,
+        status: MigrationStatus::parse(&status)?,
+        dry_run: row.try_get("dry_run")?,
+        rollback_enabled: row.try_get("rollback_enabled")?,
+        batch_size: count("batch_size")?,
+        total_workflows: count("total_workflows")?,
+        migrated_workflow_ids: serde_json::from_value(migrated)?,
+        failed_migrations: count("failed_migrations")?,
+        remaining_workflows: count("remaining_workflows")?,
+        errors: serde_json::from_value(errors)?,
+        started_at: row.try_get("started_at")?,
+        updated_at: row.try_get("updated_at")?,
+        completed_at: row.try_get("completed_at")?,
+        rollback_reason: row.try_get("rollback_reason")?,
+    })
+}
+
+pub struct PostgresWorkflowVersionRepository {
+    pool: PgPool,
+}
+
+impl PostgresWorkflowVersionRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WorkflowVersionRepository for PostgresWorkflowVersionRepository {
+    async fn list_versions(&self, workflow_type: &str) -> WorkflowServiceResult<Vec<WorkflowVersionRecord>> {
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM workflow_versions WHERE workflow_type = $1",
+            VERSION_COLUMNS
+        ))
+        .bind(workflow_type)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter().map(version_from_row).collect()
+    }
+
+    async fn get_version(&self, workflow_type: &str, version: &str) -> WorkflowServiceResult<Option<WorkflowVersionRecord>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM workflow_versions WHERE workflow_type = $1 AND version = $2",
+            VERSION_COLUMNS
+        ))
+        .bind(workflow_type)
+        .bind(version)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.as_ref().map(version_from_row).transpose()
+    }
+
+    async fn create_version(&self, version: &WorkflowVersionRecord) -> WorkflowServiceResult<()> {
+        sqlx::query(
+            "INSERT INTO workflow_versions (workflow_type, version, status, is_default, description, input_schema, \
+             breaking_changes, migration_notes, registered_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+        )
+        .bind(&version.workflow_type)
+        .bind(&version.version)
+        .bind(version.status.as_str())
+        .bind(version.is_default)
+        .bind(&version.description)
+        .bind(&version.schema)
+        .bind(serde_json::to_value(&version.breaking_changes)?)
+        .bind(&version.migration_notes)
+        .bind(version.registered_at)
+        .execute(&self.pool)
+        .await
+        .map_err(|e| name_taken(e, "version", &version.version))?;
+
+        Ok(())
+    }
+
+    async fn set_default(&self, workflow_type: &str, version: &str) -> WorkflowServiceResult<()> {
+        let mut tx = self.pool.begin().await?;
+
+        sqlx::query("UPDATE workflow_versions SET is_default = false WHERE workflow_type = $1 AND is_default")
+            .bind(workflow_type)
+            .execute(&mut *tx)
+            .await?;
+        sqlx::query("UPDATE workflow_versions SET is_default = true WHERE workflow_type = $1 AND version = $2")
+            .bind(workflow_type)
+            .bind(version)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn set_status(&self, workflow_type: &str, version: &str, status: VersionStatus) -> WorkflowServiceResult<()> {
+        sqlx::query(
+            "UPDATE workflow_versions SET status = $3, \
+             deprecated_at = CASE WHEN $3 IN ('deprecated', 'sunset') THEN COALESCE(deprecated_at, NOW()) END, \
+             deprecation_reason = CASE WHEN $3 IN ('deprecated', 'sunset') THEN deprecation_reason END \
+             WHERE workflow_type = $1 AND version = $2",
+        )
+        .bind(workflow_type)
+        .bind(version)
+        .bind(status.as_str())
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn deprecate(
+        &self,
+        workflow_type: &str,
+        version: &str,
+        reason: &str,
+        sunset_date: Option<DateTime<Utc>>,
+        migration_deadline: Option<DateTime<Utc>>,
+    ) -> WorkflowServiceResult<()> {
+        sqlx::query(
+            "UPDATE workflow_versions SET status = 'deprecated', deprecated_at = NOW(), deprecation_reason = $3, \
+             sunset_date = $4, migration_deadline = $5 WHERE workflow_type = $1 AND version = $2",
+        )
+        .bind(workflow_type)
+        .bind(version)
+        .bind(reason)
+        .bind(sunset_date)
+        .bind(migration_deadline)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn running_counts(&self, workflow_type: &str) -> WorkflowServiceResult<HashMap<String, u32>> {
+        let rows = sqlx::query(
+            "SELECT workflow_version, COUNT(*) AS running FROM workflow_executions \
+             WHERE workflow_type = $1 AND status = 'running' AND workflow_version IS NOT NULL \
+             GROUP BY workflow_version",
+        )
+        .bind(workflow_type)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let running: i64 = row.try_get("running")?;
+                Ok((row.try_get("workflow_version")?, running.max(0) as u32))
+            })
+            .collect()
+    }
+
+    async fn running_executions(
+        &self,
+        workflow_type: &str,
+        version: &str,
+        workflow_ids: Option<&[String]>,
+        limit: u32,
+    ) -> WorkflowServiceResult<Vec<VersionedExecution>> {
+        let rows = sqlx::query(
+            "SELECT tenant_id, workflow_id, run_id, workflow_version, started_at FROM workflow_executions \
+             WHERE workflow_type = $1 AND workflow_version = $2 AND status = 'running' \
+             AND ($3::TEXT[] IS NULL OR workflow_id = ANY($3)) \
+             ORDER BY started_at LIMIT $4",
+        )
+        .bind(workflow_type)
+        .bind(version)
+        .bind(workflow_ids)
+        .bind(limit as i64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let tenant_id: Uuid = row.try_get("tenant_id")?;
+                Ok(VersionedExecution {
+                    tenant_id: tenant_id.to_string(),
+                    workflow_id: row.try_get("workflow_id")?,
+                    run_id: row.try_get("run_id")?,
+                    version: row.try_get("workflow_version")?,
+                    started_at: row.try_get("started_at")?,
+                })
+            })
+            .collect()
+    }
+
+    async fn close_execution(
+        &self,
+        workflow_id: &str,
+        run_id: &str,
+        status: &str,
+        closed_at: DateTime<Utc>,
+    ) -> WorkflowServiceResult<()> {
+        sqlx::query(
+            "UPDATE workflow_executions SET status = $3, completed_at = $4 \
+             WHERE workflow_id = $1 AND run_id = $2 AND status = 'running'",
+        )
+        .bind(workflow_id)
+        .bind(run_id)
+        .bind(status)
+        .bind(closed_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn retag_executions(
+        &self,
+        workflow_type: &str,
+        from_version: &str,
+        to_version: &str,
+        workflow_ids: &[String],
+    ) -> WorkflowServiceResult<Vec<String>> {
+        let rows = sqlx::query(
+            "UPDATE workflow_executions SET workflow_version = $3 \
+             WHERE workflow_type = $1 AND workflow_version = $2 AND status = 'running' AND workflow_id = ANY($4) \
+             RETURNING workflow_id",
+        )
+        .bind(workflow_type)
+        .bind(from_version)
+        .bind(to_version)
+        .bind(workflow_ids)
+        .fetch_all(&self.pool)
+        .await?;
+
+        let mut moved = rows
+            .iter()
+            .map(|row| row.try_get("workflow_id"))
+            .collect::<Result<Vec<String>, _>>()?;
+        moved.sort();
+        moved.dedup();
+        Ok(moved)
+    }
+
+    async fn create_rollout(&self, rollout: &VersionRollout) -> WorkflowServiceResult<()> {
+        sqlx::query(
+            "INSERT INTO workflow_version_rollouts (id, workflow_type, from_version, to_version, strategy, status, \
+             dry_run, rollback_enabled, batch_size, total_workflows, migrated_workflow_ids, failed_migrations, \
+             remaining_workflows, errors, started_at, updated_at, completed_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, $17)",
+        )
+        .bind(require_id(&rollout.migration_id, "migration")?)
+        .bind(&rollout.workflow_type)
+        .bind(&rollout.from_version)
+        .bind(&rollout.to_version)
+        .bind(rollout.strategy.as_str())
+        .bind(rollout.status.as_str())
+        .bind(rollout.dry_run)
+        .bind(rollout.rollback_enabled)
+        .bind(rollout.batch_size as i32)
+        .bind(rollout.total_workflows as i32)
+        .bind(serde_json::to_value(&rollout.migrated_workflow_ids)?)
+        .bind(rollout.failed_migrations as i32)
+        .bind(rollout.remaining_workflows as i32)
+        .bind(serde_json::to_value(&rollout.errors)?)
+        .bind(rollout.started_at)
+        .bind(rollout.updated_at)
+        .bind(rollout.completed_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+
+    async fn get_rollout(&self, migration_id: &str) -> WorkflowServiceResult<Option<VersionRollout>> {
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM workflow_version_rollouts WHERE id = $1",
+            ROLLOUT_COLUMNS
+        ))
+        .bind(require_id(migration_id, "migration")?)
+        .fetch_optional(&self.pool)
+        .await?;
+
+        row.as_ref().map(rollout_from_row).transpose()
+    }
+
+    async fn update_rollout(&self, rollout: &VersionRollout) -> WorkflowServiceResult<()> {
+        sqlx::query(
+            "UPDATE workflow_version_rollouts SET status = $2, total_workflows = $3, migrated_workflow_ids = $4, \
+             failed_migrations = $5, remaining_workflows = $6, errors = $7, rollback_reason = $8, \
+             updated_at = $9, completed_at = $10 WHERE id = $1",
+        )
+        .bind(require_id(&rollout.migration_id, "migration")?)
+        .bind(rollout.status.as_str())
+        .bind(rollout.total_workflows as i32)
+        .bind(serde_json::to_value(&rollout.migrated_workflow_ids)?)
+        .bind(rollout.failed_migrations as i32)
+        .bind(rollout.remaining_workflows as i32)
+        .bind(serde_json::to_value(&rollout.errors)?)
+        .bind(&rollout.rollback_reason)
+        .bind(rollout.updated_at)
+        .bind(rollout.completed_at)
+        .execute(&self.pool)
+        .await?;
+
+        Ok(())
+    }
+}
+
+const SLA_COLUMNS: &str = "tenant_id, workflow_type, max_duration_seconds, max_failure_rate, window_minutes, min_runs, \
+     webhook_url, webhook_secret, is_enabled, created_by, created_at, updated_at";
+
+fn sla_from_row(row: &PgRow) -> WorkflowServiceResult<WorkflowSla> {
+    let tenant_id: Uuid = row.try_get("tenant_id")?;
+    let max_duration_seconds: Option<i64> = row.try_get("max_duration_seconds")?;
+    let window_minutes: i32 = row.try_get("window_minutes")?;
+    let min_runs: i32 = row.try_get("min_runs")?;
+    let created_by: Uuid = row.try_get("created_by")?;
+
+    Ok(WorkflowSla {
+        tenant_id: tenant_id.to_string(),
+        workflow_type: row.try_get("workflow_type")?,
+        max_duration_seconds: max_duration_seconds.map(|seconds| seconds as u64),
+        max_failure_rate: row.try_get("max_failure_rate")?,
+        window_minutes: window_minutes as u32,
+        min_runs: min_runs as u32,
+        webhook_url: row.try_get("webhook_url")?,
+        webhook_secret: row.try_get("webhook_secret")?,
+        is_enabled: row.try_get("is_enabled")?,
+        created_by: created_by.to_string(),
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+const SLA_BREACH_COLUMNS: &str = "id, tenant_id, workflow_type, kind, workflow_id, threshold, observed, status, \
+     detected_at, resolved_at, notified_at, notification_error";
+
+fn sla_breach_from_row(row: &PgRow) -> WorkflowServiceResult<SlaBreach> {
+    let breach_id: Uuid = row.try_get("id")?;
+    let tenant_id: Uuid = row.try_get("tenant_id")?;
+    let kind: String = row.try_get("kind")?;
+    let status: String = row.try_get("status")?;
+
+    Ok(SlaBreach {
+        breach_id: breach_id.to_string(),
+        tenant_id: tenant_id.to_string(),
+        workflow_type: row.try_get("workflow_type")?,
+        kind: SlaBreachKind::parse(&kind)?,
+        workflow_id: row.try_get("workflow_id")?,
+        threshold: row.try_get("threshold")?,
+        observed: row.try_get("observed")?,
+        status: SlaBreachStatus::parse(&status)?,
+        detected_at: row.try_get("detected_at")?,
+        resolved_at: row.try_get("resolved_at")?,
+        notified_at: row.try_get("notified_at")?,
+        notification_error: row.try_get("notification_error")?,
+    })
+}
+
+pub struct PostgresWorkflowSlaRepository {
+    pool: PgPool,
+}
+
+impl PostgresWorkflowSlaRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WorkflowSlaRepository for PostgresWorkflowSlaRepository {
+    async fn list_slas(&self, tenant_id: &str) -> WorkflowServiceResult<Vec<WorkflowSla>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM workflow_slas WHERE tenant_id = $1 ORDER BY workflow_type",
+            SLA_COLUMNS
+        ))
+        .bind(require_id(tenant_id, "tenant")?)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        rows.iter().map(sla_from_row).collect()
+    }
+
+    async fn get_sla(&self, tenant_id: &str, workflow_type: &str) -> WorkflowServiceResult<Option<WorkflowSla>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM workflow_slas WHERE tenant_id = $1 AND workflow_type = $2",
+            SLA_COLUMNS
+        ))
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(workflow_type)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        row.as_ref().map(sla_from_row).transpose()
+    }
+
+    async fn set_sla(&self, sla: &WorkflowSla) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, &sla.tenant_id).await?;
+
+        sqlx::query(
+            "INSERT INTO workflow_slas (tenant_id, workflow_type, max_duration_seconds, max_failure_rate, \
+             window_minutes, min_runs, webhook_url, webhook_secret, is_enabled, created_by, created_at, updated_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12) \
+             ON CONFLICT (tenant_id, workflow_type) DO UPDATE SET \
+             max_duration_seconds = EXCLUDED.max_duration_seconds, max_failure_rate = EXCLUDED.max_failure_rate, \
+             window_minutes = EXCLUDED.window_minutes, min_runs = EXCLUDED.min_runs, \
+             webhook_url = EXCLUDED.webhook_url, webhook_secret = EXCLUDED.webhook_secret, \
+             is_enabled = EXCLUDED.is_enabled",
+        )
+        .bind(require_id(&sla.tenant_id, "tenant")?)
+        .bind(&sla.workflow_type)
+        .bind(sla.max_duration_seconds.map(|seconds| seconds as i64))
+        .bind(sla.max_failure_rate)
+        .bind(sla.window_minutes as i32)
+        .bind(sla.min_runs as i32)
+        .bind(&sla.webhook_url)
+        .bind(&sla.webhook_secret)
+        .bind(sla.is_enabled)
+        .bind(require_id(&sla.created_by, "user")?)
+        .bind(sla.created_at)
+        .bind(sla.updated_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn delete_sla(&self, tenant_id: &str, workflow_type: &str) -> WorkflowServiceResult<bool> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let result = sqlx::query("DELETE FROM workflow_slas WHERE tenant_id = $1 AND workflow_type = $2")
+            .bind(require_id(tenant_id, "tenant")?)
+            .bind(workflow_type)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn window_stats(&self, tenant_id: &str, workflow_type: &str, since: DateTime<Utc>) -> WorkflowServiceResult<SlaWindowStats> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        // Temporal runs that also metered a cost record are counted once,
+        // from the cost record
+        let row = sqlx::query(
+            "SELECT COUNT(*) AS runs, COUNT(*) FILTER (WHERE NOT succeeded) AS failed_runs, \
+             AVG(duration_seconds)::DOUBLE PRECISION AS avg_duration_seconds FROM ( \
+                 SELECT succeeded, EXTRACT(EPOCH FROM recorded_at - started_at) AS duration_seconds \
+                 FROM workflow_run_costs \
+                 WHERE tenant_id = $1 AND workflow_type = $2 AND recorded_at >= $3 AND succeeded IS NOT NULL \
+                 UNION ALL \
+                 SELECT e.status = 'completed', EXTRACT(EPOCH FROM e.completed_at - e.started_at) \
+                 FROM workflow_executions e \
+                 WHERE e.tenant_id = $1 AND e.workflow_type = $2 AND e.status <> 'running' AND e.completed_at >= $3 \
+                 AND NOT EXISTS (SELECT 1 FROM workflow_run_costs c \
+                                 WHERE c.tenant_id = e.tenant_id AND c.workflow_id = e.workflow_id) \
+             ) runs",
+        )
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(workflow_type)
+        .bind(since)
+        .fetch_one(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        let runs: i64 = row.try_get("runs")?;
+        let failed_runs: i64 = row.try_get("failed_runs")?;
+        Ok(SlaWindowStats {
+            runs: runs as u32,
+            failed_runs: failed_runs as u32,
+            avg_duration_seconds: row.try_get("avg_duration_seconds")?,
+        })
+    }
+
+    async fn running_overruns(
+        &self,
+        tenant_id: &str,
+        workflow_type: &str,
+        started_before: DateTime<Utc>,
+    ) -> WorkflowServiceResult<Vec<RunningExecution>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let rows = sqlx::query(
+            "SELECT workflow_id, run_id, started_at FROM workflow_executions \
+             WHERE tenant_id = $1 AND workflow_type = $2 AND status = 'running' AND started_at < $3 \
+             ORDER BY started_at",
+        )
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(workflow_type)
+        .bind(started_before)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        rows.iter()
+            .map(|row| {
+                Ok(RunningExecution {
+                    workflow_id: row.try_get("workflow_id")?,
+                    run_id: row.try_get("run_id")?,
+                    started_at: row.try_get("started_at")?,
+                })
+            })
+            .collect()
+    }
+
+    async fn closed_overruns(
+        &self,
+        tenant_id: &str,
+        workflow_type: &str,
+        since: DateTime<Utc>,
+        max_seconds: i64,
+    ) -> WorkflowServiceResult<Vec<ClosedOverrun>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let rows = sqlx::query(
+            "SELECT workflow_id, duration_seconds, closed_at FROM ( \
+                 SELECT workflow_id, EXTRACT(EPOCH FROM recorded_at - started_at)::BIGINT AS duration_seconds, \
+                 recorded_at AS closed_at FROM workflow_run_costs \
+                 WHERE tenant_id = $1 AND workflow_type = $2 AND recorded_at >= $3 AND started_at IS NOT NULL \
+                 UNION ALL \
+                 SELECT workflow_id, EXTRACT(EPOCH FROM completed_at - started_at)::BIGINT, completed_at \
+                 FROM workflow_executions \
+                 WHERE tenant_id = $1 AND workflow_type = $2 AND status <> 'running' AND completed_at >= $3 \
+             ) runs WHERE duration_seconds > $4 ORDER BY closed_at",
+        )
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(workflow_type)
+        .bind(since)
+        .bind(max_seconds)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        rows.iter()
+            .map(|row| {
+                Ok(ClosedOverrun {
+                    workflow_id: row.try_get("workflow_id")?,
+                    duration_seconds: row.try_get("duration_seconds")?,
+                    closed_at: row.try_get("closed_at")?,
+                })
+            })
+            .collect()
+    }
+
+    async fn close_execution(
+        &self,
+        tenant_id: &str,
+        workflow_id: &str,
+        run_id: &str,
+        status: &str,
+        closed_at: DateTime<Utc>,
+    ) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        sqlx::query(
+            "UPDATE workflow_executions SET status = $4, completed_at = $5 \
+             WHERE tenant_id = $1 AND workflow_id = $2 AND run_id = $3 AND status = 'running'",
+        )
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(workflow_id)
+        .bind(run_id)
+        .bind(status)
+        .bind(closed_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn record_breach(&self, breach: &SlaBreach) -> WorkflowServiceResult<bool> {
+        let mut tx = begin_tenant(&self.pool, &breach.tenant_id).await?;
+
+        let result = sqlx::query(
+            "INSERT INTO workflow_sla_breaches (id, tenant_id, workflow_type, kind, workflow_id, threshold, observed, \
+             status, detected_at, resolved_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10) \
+             ON CONFLICT DO NOTHING",
+        )
+        .bind(require_id(&breach.breach_id, "breach")?)
+        .bind(require_id(&breach.tenant_id, "tenant")?)
+        .bind(&breach.workflow_type)
+        .bind(breach.kind.as_str())
+        .bind(&breach.workflow_id)
+        .bind(breach.threshold)
+        .bind(breach.observed)
+        .bind(breach.status.as_str())
+        .bind(breach.detected_at)
+        .bind(breach.resolved_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn resolve_finished_overruns(&self, tenant_id: &str, workflow_type: &str, at: DateTime<Utc>) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        sqlx::query(
+            "UPDATE workflow_sla_breaches b SET status = 'resolved', resolved_at = $3 \
+             WHERE b.tenant_id = $1 AND b.workflow_type = $2 AND b.kind = 'duration' AND b.status = 'open' \
+             AND NOT EXISTS (SELECT 1 FROM workflow_executions e WHERE e.tenant_id = b.tenant_id \
+                             AND e.workflow_id = b.workflow_id AND e.status = 'running')",
+        )
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(workflow_type)
+        .bind(at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn resolve_failure_rate(&self, tenant_id: &str, workflow_type: &str, at: DateTime<Utc>) -> WorkflowServiceResult<Option<SlaBreach>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let row = sqlx::query(&format!(
+            "UPDATE workflow_sla_breaches SET status = 'resolved', resolved_at = $3 \
+             WHERE tenant_id = $1 AND workflow_type = $2 AND kind = 'failure_rate' AND status = 'open' \
+             RETURNING {}",
+            SLA_BREACH_COLUMNS
+        ))
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(workflow_type)
+        .bind(at)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        row.as_ref().map(sla_breach_from_row).transpose()
+    }
+
+    async fn record_notification(&self, tenant_id: &str, breach_id: &str, error: Option<&str>) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        sqlx::query(
+            "UPDATE workflow_sla_breaches SET notified_at = NOW(), notification_error = $3 \
+             WHERE tenant_id = $1 AND id = $2",
+        )
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(require_id(breach_id, "breach")?)
+        .bind(error)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn list_breaches(
+        &self,
+        tenant_id: &str,
+        status: Option<SlaBreachStatus>,
+        workflow_type: Option<&str>,
+        limit: u32,
+        offset: u32,
+    ) -> WorkflowServiceResult<(Vec<SlaBreach>, u64)> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+        let tenant_id = require_id(tenant_id, "tenant")?;
+        let status = status.map(|s| s.as_str());
+        let filter = "tenant_id = $1 AND ($2::TEXT IS NULL OR status = $2) AND ($3::TEXT IS NULL OR workflow_type = $3)";
+
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM workflow_sla_breaches WHERE {} ORDER BY detected_at DESC LIMIT $4 OFFSET $5",
+            SLA_BREACH_COLUMNS, filter
+        ))
+        .bind(tenant_id)
+        .bind(status)
+        .bind(workflow_type)
+        .bind(limit as i64)
+        .bind(offset as i64)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        let total: i64 = sqlx::query_scalar(&format!("SELECT COUNT(*) FROM workflow_sla_breaches WHERE {}", filter))
+            .bind(tenant_id)
+            .bind(status)
+            .bind(workflow_type)
+            .fetch_one(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        let breaches = rows.iter().map(sla_breach_from_row).collect::<WorkflowServiceResult<Vec<_>>>()?;
+        Ok((breaches, total.max(0) as u64))
+    }
+}
+
+const CONTROL_ACTION_COLUMNS: &str =
+    "id, tenant_id, workflow_id, run_id, workflow_type, action, reason, requested_by, inline_run, requested_at";
+
+fn control_action_from_row(row: &PgRow) -> WorkflowServiceResult<WorkflowControlRecord> {
+    let action_id: Uuid = row.try_get("id")?;
+    let tenant_id: Uuid = row.try_get("tenant_id")?;
+    let action: String = row.try_get("action")?;
+    let requested_by: Uuid = row.try_get("requested_by")?;
+
+    Ok(WorkflowControlRecord {
+        action_id: action_id.to_string(),
+        tenant_id: tenant_id.to_string(),
+        workflow_id: row.try_get("workflow_id")?,
+        run_id: row.try_get("run_id")?,
+        workflow_type: row.try_get("workflow_type")?,
+        action: ControlAction::parse(&action)?,
+        reason: row.try_get("reason")?,
+        requested_by: requested_by.to_string(),
+        inline_run: row.try_get("inline_run")?,
+        requested_at: row.try_get("requested_at")?,
+    })
+}
+
+pub struct PostgresWorkflowControlRepository {
+    pool: PgPool,
+}
+
+impl PostgresWorkflowControlRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WorkflowControlRepository for PostgresWorkflowControlRepository {
+    async fn record_action(&self, record: &WorkflowControlRecord) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, &record.tenant_id).await?;
+
+        sqlx::query(
+            "INSERT INTO workflow_control_actions (id, tenant_id, workflow_id, run_id, workflow_type, action, \
+             reason, requested_by, inline_run, requested_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)",
+        )
+        .bind(require_id(&record.action_id, "control action")?)
+        .bind(require_id(&record.tenant_id, "tenant")?)
+        .bind(&record.workflow_id)
+        .bind(&record.run_id)
+        .bind(&record.workflow_type)
+        .bind(record.action.as_str())
+        .bind(&record.reason)
+        .bind(require_id(&record.requested_by, "user")?)
+        .bind(record.inline_run)
+        .bind(record.requested_at)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn list_actions(&self, tenant_id: &str, workflow_id: &str) -> WorkflowServiceResult<Vec<WorkflowControlRecord>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM workflow_control_actions WHERE tenant_id = $1 AND workflow_id = $2 \
+             ORDER BY requested_at DESC",
+            CONTROL_ACTION_COLUMNS
+        ))
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(workflow_id)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        rows.iter().map(control_action_from_row).collect()
+    }
+
+    async fn last_pause_action(&self, tenant_id: &str, workflow_id: &str) -> WorkflowServiceResult<Option<ControlAction>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let action: Option<String> = sqlx::query_scalar(
+            "SELECT action FROM workflow_control_actions \
+             WHERE tenant_id = $1 AND workflow_id = $2 AND action IN ('pause', 'resume') \
+             ORDER BY requested_at DESC LIMIT 1",
+        )
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(workflow_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        action.as_deref().map(ControlAction::parse).transpose()
+    }
+
+    async fn execution_owner(&self, tenant_id: &str, workflow_id: &str) -> WorkflowServiceResult<Option<String>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let owner: Option<Option<Uuid>> = sqlx::query_scalar(
+            "SELECT user_id FROM workflow_executions WHERE tenant_id = $1 AND workflow_id = $2 \
+             ORDER BY started_at DESC LIMIT 1",
+        )
+        .bind(require_id(tenant_id, "tenant")?)
+        .bind(workflow_id)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(owner.flatten().map(|user_id| user_id.to_string()))
+    }
+}
+
+const TRIGGER_COLUMNS: &str = "id, tenant_id, name, description, event_type, filter, workflow_type, input_template, \
+     enabled, created_by, last_fired_at, created_at, updated_at";
+
+fn trigger_from_row(row: &PgRow) -> WorkflowServiceResult<WorkflowTrigger> {
+    let trigger_id: Uuid = row.try_get("id")?;
+    let tenant_id: Uuid = row.try_get("tenant_id")?;
+    let created_by: Option<Uuid> = row.try_get("created_by")?;
+    let filter: serde_json::Value = row.try_get("filter")?;
+
+    Ok(WorkflowTrigger {
+        trigger_id: trigger_id.to_string(),
+        tenant_id: tenant_id.to_string(),
+        name: row.try_get("name")?,
+        description: row.try_get("description")?,
+        event_type: row.try_get("event_type")?,
+        filter: serde_json::from_value(filter)?,
+        workflow_type: row.try_get("workflow_type")?,
+        input_template: row.try_get("input_template")?,
+        enabled: row.try_get("enabled")?,
+        created_by: created_by.map(|id| id.to_string()),
+        last_fired_at: row.try_get("last_fired_at")?,
+        created_at: row.try_get("created_at")?,
+        updated_at: row.try_get("updated_at")?,
+    })
+}
+
+const TRIGGER_EVENT_COLUMNS: &str = "id, tenant_id, event_type, source, subject, data, status, attempts, error, \
+     occurred_at, received_at, dispatched_at";
+
+fn trigger_event_from_row(row: &PgRow) -> WorkflowServiceResult<TriggerEvent> {
+    let event_id: Uuid = row.try_get("id")?;
+    let tenant_id: Uuid = row.try_get("tenant_id")?;
+    let status: String = row.try_get("status")?;
+    let attempts: i32 = row.try_get("attempts")?;
+
+    Ok(TriggerEvent {
+        event_id: event_id.to_string(),
+        tenant_id: tenant_id.to_string(),
+        event_type: row.try_get("event_type")?,
+        source: row.try_get("source")?,
+        subject: row.try_get("subject")?,
+        data: row.try_get("data")?,
+        status: TriggerEventStatus::parse(&status)?,
+        attempts: attempts.max(0) as u32,
+        error: row.try_get("error")?,
+        occurred_at: row.try_get("occurred_at")?,
+        received_at: row.try_get("received_at")?,
+        dispatched_at: row.try_get("dispatched_at")?,
+    })
+}
+
+const TRIGGER_FIRING_COLUMNS: &str = "id, tenant_id, trigger_id, event_id, workflow_id, status, error, fired_at";
+
+fn trigger_firing_from_row(row: &PgRow) -> WorkflowServiceResult<TriggerFiring> {
+    let firing_id: Uuid = row.try_get("id")?;
+    let tenant_id: Uuid = row.try_get("tenant_id")?;
+    let trigger_id: Uuid = row.try_get("trigger_id")?;
+    let event_id: Uuid = row.try_get("event_id")?;
+    let status: String = row.try_get("status")?;
+
+    Ok(TriggerFiring {
+        firing_id: firing_id.to_string(),
+        tenant_id: tenant_id.to_string(),
+        trigger_id: trigger_id.to_string(),
+        event_id: event_id.to_string(),
+        workflow_id: row.try_get("workflow_id")?,
+        status: FiringStatus::parse(&status)?,
+        error: row.try_get("error")?,
+        fired_at: row.try_get("fired_at")?,
+    })
+}
+
+pub struct PostgresWorkflowTriggerRepository {
+    pool: PgPool,
+}
+
+impl PostgresWorkflowTriggerRepository {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+}
+
+#[async_trait]
+impl WorkflowTriggerRepository for PostgresWorkflowTriggerRepository {
+    async fn create_trigger(&self, trigger: &WorkflowTrigger) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, &trigger.tenant_id).await?;
+
+        sqlx::query(
+            "INSERT INTO workflow_triggers (id, tenant_id, name, description, event_type, filter, workflow_type, \
+             input_template, enabled, created_by, created_at, updated_at) \
+             VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12)",
+        )
+        .bind(require_id(&trigger.trigger_id, "trigger")?)
+        .bind(require_id(&trigger.tenant_id, "tenant")?)
+        .bind(&trigger.name)
+        .bind(&trigger.description)
+        .bind(&trigger.event_type)
+        .bind(serde_json::Value::Object(trigger.filter.clone()))
+        .bind(&trigger.workflow_type)
+        .bind(&trigger.input_template)
+        .bind(trigger.enabled)
+        .bind(optional_id(trigger.created_by.as_deref(), "user")?)
+        .bind(trigger.created_at)
+        .bind(trigger.updated_at)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| name_taken(e, "trigger", &trigger.name))?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn get_trigger(&self, tenant_id: &str, trigger_id: &str) -> WorkflowServiceResult<Option<WorkflowTrigger>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let row = sqlx::query(&format!("SELECT {} FROM workflow_triggers WHERE id = $1", TRIGGER_COLUMNS))
+            .bind(require_id(trigger_id, "trigger")?)
+            .fetch_optional(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        row.as_ref().map(trigger_from_row).transpose()
+    }
+
+    async fn list_triggers(&self, tenant_id: &str) -> WorkflowServiceResult<Vec<WorkflowTrigger>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let rows = sqlx::query(&format!("SELECT {} FROM workflow_triggers ORDER BY name", TRIGGER_COLUMNS))
+            .fetch_all(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        rows.iter().map(trigger_from_row).collect()
+    }
+
+    async fn enabled_triggers(&self, tenant_id: &str) -> WorkflowServiceResult<Vec<WorkflowTrigger>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM workflow_triggers WHERE enabled ORDER BY created_at",
+            TRIGGER_COLUMNS
+        ))
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        rows.iter().map(trigger_from_row).collect()
+    }
+
+    async fn count_triggers(&self, tenant_id: &str) -> WorkflowServiceResult<u32> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let count: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM workflow_triggers")
+            .fetch_one(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(count.max(0) as u32)
+    }
+
+    async fn update_trigger(&self, trigger: &WorkflowTrigger) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, &trigger.tenant_id).await?;
+
+        sqlx::query(
+            "UPDATE workflow_triggers SET name = $2, description = $3, event_type = $4, filter = $5, \
+             workflow_type = $6, input_template = $7, enabled = $8 WHERE id = $1",
+        )
+        .bind(require_id(&trigger.trigger_id, "trigger")?)
+        .bind(&trigger.name)
+        .bind(&trigger.description)
+        .bind(&trigger.event_type)
+        .bind(serde_json::Value::Object(trigger.filter.clone()))
+        .bind(&trigger.workflow_type)
+        .bind(&trigger.input_template)
+        .bind(trigger.enabled)
+        .execute(&mut *tx)
+        .await
+        .map_err(|e| name_taken(e, "trigger", &trigger.name))?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn delete_trigger(&self, tenant_id: &str, trigger_id: &str) -> WorkflowServiceResult<bool> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let result = sqlx::query("DELETE FROM workflow_triggers WHERE id = $1")
+            .bind(require_id(trigger_id, "trigger")?)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(result.rows_affected() > 0)
+    }
+
+    async fn record_event(&self, event: &TriggerEvent) -> WorkflowServiceResult<bool> {
+        let mut tx = begin_tenant(&self.pool, &event.tenant_id).await?;
+        let event_id = require_id(&event.event_id, "event")?;
+        let tenant_id = require_id(&event.tenant_id, "tenant")?;
+
+        let inserted = sqlx::query(
+            "INSERT INTO workflow_trigger_events (id, tenant_id, event_type, source, subject, data, occurred_at, \
+             received_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) ON CONFLICT (id) DO NOTHING",
+        )
+        .bind(event_id)
+        .bind(tenant_id)
+        .bind(&event.event_type)
+        .bind(&event.source)
+        .bind(&event.subject)
+        .bind(&event.data)
+        .bind(event.occurred_at)
+        .bind(event.received_at)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected()
+            > 0;
+
+        if inserted {
+            sqlx::query("INSERT INTO workflow_trigger_dispatch_queue (event_id, tenant_id) VALUES ($1, $2)")
+                .bind(event_id)
+                .bind(tenant_id)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(inserted)
+    }
+
+    async fn get_event(&self, tenant_id: &str, event_id: &str) -> WorkflowServiceResult<Option<TriggerEvent>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let row = sqlx::query(&format!(
+            "SELECT {} FROM workflow_trigger_events WHERE id = $1",
+            TRIGGER_EVENT_COLUMNS
+        ))
+        .bind(require_id(event_id, "event")?)
+        .fetch_optional(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        row.as_ref().map(trigger_event_from_row).transpose()
+    }
+
+    async fn claim_events(&self, limit: u32, lease_seconds: u64) -> WorkflowServiceResult<Vec<QueuedEvent>> {
+        // The queue has no row level security, so this is the one query that
+        // sees every tenant's events
+        let rows = sqlx::query(
+            "UPDATE workflow_trigger_dispatch_queue \
+             SET attempts = attempts + 1, available_at = NOW() + make_interval(secs => $2) \
+             WHERE event_id IN ( \
+                 SELECT event_id FROM workflow_trigger_dispatch_queue WHERE available_at <= NOW() \
+                 ORDER BY available_at LIMIT $1 FOR UPDATE SKIP LOCKED \
+             ) \
+             RETURNING event_id, tenant_id, attempts",
+        )
+        .bind(limit as i64)
+        .bind(lease_seconds as f64)
+        .fetch_all(&self.pool)
+        .await?;
+
+        rows.iter()
+            .map(|row| {
+                let event_id: Uuid = row.try_get("event_id")?;
+                let tenant_id: Uuid = row.try_get("tenant_id")?;
+                let attempts: i32 = row.try_get("attempts")?;
+                Ok(QueuedEvent {
+                    event_id: event_id.to_string(),
+                    tenant_id: tenant_id.to_string(),
+                    attempts: attempts.max(0) as u32,
+                })
+            })
+            .collect()
+    }
+
+    async fn retry_event(&self, queued: &QueuedEvent, delay_seconds: u64, error: &str) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, &queued.tenant_id).await?;
+        let event_id = require_id(&queued.event_id, "event")?;
+
+        sqlx::query(
+            "UPDATE workflow_trigger_dispatch_queue SET available_at = NOW() + make_interval(secs => $2) \
+             WHERE event_id = $1",
+        )
+        .bind(event_id)
+        .bind(delay_seconds as f64)
+        .execute(&mut *tx)
+        .await?;
+
+        sqlx::query("UPDATE workflow_trigger_events SET attempts = $2, error = $3 WHERE id = $1")
+            .bind(event_id)
+            .bind(queued.attempts as i32)
+            .bind(error)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn finish_event(&self, queued: &QueuedEvent, status: TriggerEventStatus, error: Option<&str>) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, &queued.tenant_id).await?;
+        let event_id = require_id(&queued.event_id, "event")?;
+
+        sqlx::query("DELETE FROM workflow_trigger_dispatch_queue WHERE event_id = $1")
+            .bind(event_id)
+            .execute(&mut *tx)
+            .await?;
+
+        sqlx::query(
+            "UPDATE workflow_trigger_events SET status = $2, attempts = $3, error = $4, \
+             dispatched_at = CASE WHEN $2 = 'dispatched' THEN NOW() END WHERE id = $1",
+        )
+        .bind(event_id)
+        .bind(status.as_str())
+        .bind(queued.attempts as i32)
+        .bind(error)
+        .execute(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn claim_firing(&self, firing: &TriggerFiring) -> WorkflowServiceResult<bool> {
+        let mut tx = begin_tenant(&self.pool, &firing.tenant_id).await?;
+        let trigger_id = require_id(&firing.trigger_id, "trigger")?;
+
+        let claimed = sqlx::query(
+            "INSERT INTO workflow_trigger_firings (id, tenant_id, trigger_id, event_id, workflow_id, status, error, \
+             fired_at) VALUES ($1, $2, $3, $4, $5, $6, $7, $8) ON CONFLICT (trigger_id, event_id) DO NOTHING",
+        )
+        .bind(require_id(&firing.firing_id, "firing")?)
+        .bind(require_id(&firing.tenant_id, "tenant")?)
+        .bind(trigger_id)
+        .bind(require_id(&firing.event_id, "event")?)
+        .bind(&firing.workflow_id)
+        .bind(firing.status.as_str())
+        .bind(&firing.error)
+        .bind(firing.fired_at)
+        .execute(&mut *tx)
+        .await?
+        .rows_affected()
+            > 0;
+
+        if claimed && firing.status == FiringStatus::Started {
+            sqlx::query("UPDATE workflow_triggers SET last_fired_at = $2 WHERE id = $1")
+                .bind(trigger_id)
+                .bind(firing.fired_at)
+                .execute(&mut *tx)
+                .await?;
+        }
+
+        tx.commit().await?;
+        Ok(claimed)
+    }
+
+    async fn release_firing(&self, tenant_id: &str, firing_id: &str) -> WorkflowServiceResult<()> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        sqlx::query("DELETE FROM workflow_trigger_firings WHERE id = $1")
+            .bind(require_id(firing_id, "firing")?)
+            .execute(&mut *tx)
+            .await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn list_firings(&self, tenant_id: &str, trigger_id: &str, limit: u32) -> WorkflowServiceResult<Vec<TriggerFiring>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM workflow_trigger_firings WHERE trigger_id = $1 ORDER BY fired_at DESC LIMIT $2",
+            TRIGGER_FIRING_COLUMNS
+        ))
+        .bind(require_id(trigger_id, "trigger")?)
+        .bind(limit as i64)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        rows.iter().map(trigger_firing_from_row).collect()
+    }
+
+    async fn event_firings(&self, tenant_id: &str, event_id: &str) -> WorkflowServiceResult<Vec<TriggerFiring>> {
+        let mut tx = begin_tenant(&self.pool, tenant_id).await?;
+
+        let rows = sqlx::query(&format!(
+            "SELECT {} FROM workflow_trigger_firings WHERE event_id = $1 ORDER BY fired_at",
+            TRIGGER_FIRING_COLUMNS
+        ))
+        .bind(require_id(event_id, "event")?)
+        .fetch_all(&mut *tx)
+        .await?;
+
+        tx.commit().await?;
+        rows.iter().map(trigger_firing_from_row).collect()
+    }
+}
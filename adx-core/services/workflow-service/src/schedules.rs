@@ -0,0 +1,634 @@
+use crate::{
+    config::WorkflowServiceConfig,
+    cron::CronExpression,
+    error::{WorkflowServiceError, WorkflowServiceResult},
+    models::*,
+    repositories::WorkflowScheduleRepository,
+    task_queues::TaskQueueRouter,
+};
+use adx_shared::temporal::{AdxTemporalClient, ScheduleAction, ScheduleSpec};
+use chrono::{DateTime, NaiveDate, Utc};
+use chrono_tz::Tz;
+use serde::{Deserialize, Serialize};
+use std::{sync::Arc, time::Duration};
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Workflows that make sense to run unattended. Onboarding and tenant
+/// switching act on behalf of a signed-in user and are not schedulable.
+pub const SCHEDULABLE_WORKFLOW_TYPES: [&str; 4] = [
+    "data_migration_workflow",
+    "bulk_operation_workflow",
+    "compliance_workflow",
+    "module_job_workflow",
+];
+
+/// Runs shown when a schedule is read back
+const UPCOMING_RUNS_SHOWN: usize = 5;
+/// Runs sampled when checking a cron expression against the minimum interval
+const INTERVAL_SAMPLES: usize = 50;
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum ScheduleState {
+    Active,
+    Paused,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowSchedule {
+    /// Also the id of the Temporal schedule
+    pub schedule_id: String,
+    pub tenant_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    pub workflow_type: String,
+    pub task_queue: String,
+    pub cron_expression: String,
+    pub timezone: String,
+    pub jitter_seconds: u32,
+    pub calendar_id: Option<String>,
+    pub input: serde_json::Value,
+    pub state: ScheduleState,
+    pub paused_reason: Option<String>,
+    pub paused_at: Option<DateTime<Utc>>,
+    pub next_run_at: Option<DateTime<Utc>>,
+    pub last_run_at: Option<DateTime<Utc>>,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// Dates a tenant's schedules should not run on, such as public holidays
+/// or month-end freezes
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowCalendar {
+    pub calendar_id: String,
+    pub tenant_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// Used for schedules created on this calendar without a timezone
+    pub timezone: String,
+    pub excluded_dates: Vec<NaiveDate>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct ScheduleLimits {
+    pub max_schedules: u32,
+    pub min_interval_seconds: u32,
+}
+
+/// Workflow scheduling: recurring schedules persisted in the database and
+/// run by Temporal schedules, per-tenant calendars and per-tenant limits
+pub struct WorkflowScheduleManager {
+    config: Arc<WorkflowServiceConfig>,
+    repository: Arc<dyn WorkflowScheduleRepository>,
+    temporal: AdxTemporalClient,
+    task_queues: Arc<TaskQueueRouter>,
+}
+
+impl WorkflowScheduleManager {
+    pub fn new(
+        config: Arc<WorkflowServiceConfig>,
+        repository: Arc<dyn WorkflowScheduleRepository>,
+        temporal: AdxTemporalClient,
+        task_queues: Arc<TaskQueueRouter>,
+    ) -> Self {
+        Self {
+            config,
+            repository,
+            temporal,
+            task_queues,
+        }
+    }
+
+    /// Limits in force for a tenant: its override if one is set, otherwise
+    /// the service defaults
+    pub async fn get_limits(&self, tenant_id: &str) -> WorkflowServiceResult<ScheduleLimits> {
+        Ok(self.repository.get_limits(tenant_id).await?.unwrap_or(ScheduleLimits {
+            max_schedules: self.config.schedules.max_schedules_per_tenant,
+            min_interval_seconds: self.config.schedules.min_interval_seconds,
+        }))
+    }
+
+    /// Override a tenant's limits. Existing schedules are kept even if they
+    /// now exceed the limits; only new and edited schedules are checked.
+    pub async fn set_limits(&self, tenant_id: &str, request: SetScheduleLimitsRequest) -> WorkflowServiceResult<ScheduleLimits> {
+        if request.min_interval_seconds < 60 {
+            return Err(WorkflowServiceError::Validation(
+                "min_interval_seconds must be at least 60".to_string(),
+            ));
+        }
+
+        let limits = ScheduleLimits {
+            max_schedules: request.max_schedules,
+            min_interval_seconds: request.min_interval_seconds,
+        };
+        self.repository.set_limits(tenant_id, &limits).await?;
+
+        info!("Set schedule limits for tenant {}: {:?}", tenant_id, limits);
+        Ok(limits)
+    }
+
+    pub async fn create_schedule(
+        &self,
+        tenant_id: &str,
+        user_id: Option<&str>,
+        request: CreateScheduleRequest,
+    ) -> WorkflowServiceResult<ScheduleResponse> {
+        info!("Creating {} schedule '{}' for tenant {}", request.workflow_type, request.name, tenant_id);
+
+        let created_by = user_id
+            .ok_or_else(|| WorkflowServiceError::Validation("X-User-ID header is required".to_string()))?
+            .to_string();
+        if request.name.trim().is_empty() {
+            return Err(WorkflowServiceError::Validation("Schedule name is required".to_string()));
+        }
+
+        let limits = self.get_limits(tenant_id).await?;
+        let existing = self.repository.count_schedules(tenant_id).await?;
+        if existing >= limits.max_schedules {
+            return Err(WorkflowServiceError::LimitExceeded(format!(
+                "Tenant already has {} of {} allowed schedules",
+                existing, limits.max_schedules
+            )));
+        }
+
+        let calendar = self.find_calendar(tenant_id, request.calendar_id.as_deref()).await?;
+        let timezone = request
+            .timezone
+            .clone()
+            .or_else(|| calendar.as_ref().map(|c| c.timezone.clone()))
+            .unwrap_or_else(|| "UTC".to_string());
+        let task_queue = self.task_queues.route(tenant_id, &request.workflow_type).await?;
+
+        let now = Utc::now();
+        let mut schedule = WorkflowSchedule {
+            schedule_id: format!("schedule_{}", Uuid::new_v4()),
+            tenant_id: tenant_id.to_string(),
+            name: request.name.trim().to_string(),
+            description: request.description,
+            workflow_type: request.workflow_type,
+            task_queue,
+            cron_expression: request.cron_expression,
+            timezone,
+            jitter_seconds: request.jitter_seconds,
+            calendar_id: calendar.as_ref().map(|c| c.calendar_id.clone()),
+            input: request.input,
+            state: if request.start_paused { ScheduleState::Paused } else { ScheduleState::Active },
+            paused_reason: None,
+            paused_at: if request.start_paused { Some(now) } else { None },
+            next_run_at: None,
+            last_run_at: None,
+            created_by,
+            created_at: now,
+            updated_at: now,
+        };
+        schedule.next_run_at = self.validate_schedule(&schedule, calendar.as_ref(), &limits, now)?;
+
+        let (spec, action) = temporal_schedule(&schedule, calendar.as_ref());
+        self.temporal
+            .create_schedule(&schedule.schedule_id, &spec, &action, schedule.state == ScheduleState::Paused)
+            .await
+            .map_err(|e| WorkflowServiceError::Temporal(e.to_string()))?;
+
+        // Don't leave a Temporal schedule running that the service can't see
+        if let Err(e) = self.repository.create_schedule(&schedule).await {
+            if let Err(cleanup) = self.temporal.delete_schedule(&schedule.schedule_id).await {
+                warn!("Failed to remove Temporal schedule {} after a failed insert: {}", schedule.schedule_id, cleanup);
+            }
+            return Err(e);
+        }
+
+        Ok(self.respond(schedule, calendar.as_ref()))
+    }
+
+    pub async fn list_schedules(&self, tenant_id: &str) -> WorkflowServiceResult<ListSchedulesResponse> {
+        let schedules = self.repository.list_schedules(tenant_id).await?;
+
+        Ok(ListSchedulesResponse {
+            total_count: schedules.len() as u32,
+            schedules,
+            limits: self.get_limits(tenant_id).await?,
+        })
+    }
+
+    pub async fn get_schedule(&self, tenant_id: &str, schedule_id: &str) -> WorkflowServiceResult<ScheduleResponse> {
+        let schedule = self.find_schedule(tenant_id, schedule_id).await?;
+        let calendar = self.find_calendar(tenant_id, schedule.calendar_id.as_deref()).await?;
+
+        Ok(self.respond(schedule, calendar.as_ref()))
+    }
+
+    /// Replace what a schedule runs and when. The pause state is kept.
+    pub async fn update_schedule(
+        &self,
+        tenant_id: &str,
+        schedule_id: &str,
+        request: UpdateScheduleRequest,
+    ) -> WorkflowServiceResult<ScheduleResponse> {
+        info!("Updating schedule {} for tenant {}", schedule_id, tenant_id);
+
+        let mut schedule = self.find_schedule(tenant_id, schedule_id).await?;
+        let calendar = self.find_calendar(tenant_id, request.calendar_id.as_deref()).await?;
+
+        schedule.description = request.description;
+        schedule.cron_expression = request.cron_expression;
+        if let Some(timezone) = request.timezone {
+            schedule.timezone = timezone;
+        }
+        schedule.jitter_seconds = request.jitter_seconds;
+        schedule.calendar_id = calendar.as_ref().map(|c| c.calendar_id.clone());
+        schedule.input = request.input;
+        // Pick up a task queue assignment made since the schedule was created
+        schedule.task_queue = self.task_queues.route(tenant_id, &schedule.workflow_type).await?;
+
+        let now = Utc::now();
+        let limits = self.get_limits(tenant_id).await?;
+        schedule.next_run_at = self.validate_schedule(&schedule, calendar.as_ref(), &limits, now)?;
+        schedule.updated_at = now;
+
+        self.sync_to_temporal(&schedule, calendar.as_ref()).await?;
+        self.repository.update_schedule(&schedule).await?;
+
+        Ok(self.respond(schedule, calendar.as_ref()))
+    }
+
+    pub async fn delete_schedule(&self, tenant_id: &str, schedule_id: &str) -> WorkflowServiceResult<()> {
+        info!("Deleting schedule {} for tenant {}", schedule_id, tenant_id);
+
+        let schedule = self.find_schedule(tenant_id, schedule_id).await?;
+        self.temporal
+            .delete_schedule(&schedule.schedule_id)
+            .await
+            .map_err(|e| WorkflowServiceError::Temporal(e.to_string()))?;
+        self.repository.delete_schedule(tenant_id, schedule_id).await?;
+
+        Ok(())
+    }
+
+    /// Stop starting new runs. Runs already in progress finish normally.
+    pub async fn pause_schedule(
+        &self,
+        tenant_id: &str,
+        schedule_id: &str,
+        reason: Option<String>,
+    ) -> WorkflowServiceResult<ScheduleResponse> {
+        let mut schedule = self.find_schedule(tenant_id, schedule_id).await?;
+        if schedule.state == ScheduleState::Paused {
+            return Err(WorkflowServiceError::Validation(format!("Schedule {} is already paused", schedule_id)));
+        }
+
+        let note = reason.clone().unwrap_or_else(|| "Paused via workflow-service API".to_string());
+        self.temporal
+            .pause_schedule(&schedule.schedule_id, &note)
+            .await
+            .map_err(|e| WorkflowServiceError::Temporal(e.to_string()))?;
+
+        let now = Utc::now();
+        schedule.state = ScheduleState::Paused;
+        schedule.paused_reason = reason;
+        schedule.paused_at = Some(now);
+        schedule.next_run_at = None;
+        schedule.updated_at = now;
+        self.repository.update_schedule(&schedule).await?;
+
+        info!("Paused schedule {} for tenant {}", schedule_id, tenant_id);
+        let calendar = self.find_calendar(tenant_id, schedule.calendar_id.as_deref()).await?;
+        Ok(self.respond(schedule, calendar.as_ref()))
+    }
+
+    /// Start runs again from the next matching time; runs missed while
+    /// paused are not made up
+    pub async fn resume_schedule(&self, tenant_id: &str, schedule_id: &str) -> WorkflowServiceResult<ScheduleResponse> {
+        let mut schedule = self.find_schedule(tenant_id, schedule_id).await?;
+        if schedule.state == ScheduleState::Active {
+            return Err(WorkflowServiceError::Validation(format!("Schedule {} is not paused", schedule_id)));
+        }
+
+        self.temporal
+            .unpause_schedule(&schedule.schedule_id, "Resumed via workflow-service API")
+            .await
+            .map_err(|e| WorkflowServiceError::Temporal(e.to_string()))?;
+
+        let now = Utc::now();
+        let calendar = self.find_calendar(tenant_id, schedule.calendar_id.as_deref()).await?;
+        schedule.state = ScheduleState::Active;
+        schedule.paused_reason = None;
+        schedule.paused_at = None;
+        schedule.next_run_at = next_run(&schedule, calendar.as_ref(), now);
+        schedule.updated_at = now;
+        self.repository.update_schedule(&schedule).await?;
+
+        info!("Resumed schedule {} for tenant {}", schedule_id, tenant_id);
+        Ok(self.respond(schedule, calendar.as_ref()))
+    }
+
+    pub async fn create_calendar(&self, tenant_id: &str, request: CreateCalendarRequest) -> WorkflowServiceResult<WorkflowCalendar> {
+        if request.name.trim().is_empty() {
+            return Err(WorkflowServiceError::Validation("Calendar name is required".to_string()));
+        }
+        let timezone = request.timezone.unwrap_or_else(|| "UTC".to_string());
+        parse_timezone(&timezone)?;
+
+        let now = Utc::now();
+        let calendar = WorkflowCalendar {
+            calendar_id: Uuid::new_v4().to_string(),
+            tenant_id: tenant_id.to_string(),
+            name: request.name.trim().to_string(),
+            description: request.description,
+            timezone,
+            excluded_dates: normalize_dates(request.excluded_dates),
+            created_at: now,
+            updated_at: now,
+        };
+        self.repository.create_calendar(&calendar).await?;
+
+        info!("Created calendar '{}' for tenant {}", calendar.name, tenant_id);
+        Ok(calendar)
+    }
+
+    pub async fn list_calendars(&self, tenant_id: &str) -> WorkflowServiceResult<Vec<WorkflowCalendar>> {
+        self.repository.list_calendars(tenant_id).await
+    }
+
+    /// Replace a calendar's dates and push the new exclusions to every
+    /// schedule that uses it. Schedule timezones are left alone; the
+    /// calendar timezone only applies when a schedule is created.
+    pub async fn update_calendar(
+        &self,
+        tenant_id: &str,
+        calendar_id: &str,
+        request: UpdateCalendarRequest,
+    ) -> WorkflowServiceResult<WorkflowCalendar> {
+        let mut calendar = self
+            .find_calendar(tenant_id, Some(calendar_id))
+            .await?
+            .ok_or_else(|| WorkflowServiceError::NotFound(format!("Calendar {}", calendar_id)))?;
+
+        if let Some(timezone) = request.timezone {
+            parse_timezone(&timezone)?;
+            calendar.timezone = timezone;
+        }
+        calendar.description = request.description;
+        calendar.excluded_dates = normalize_dates(request.excluded_dates);
+        calendar.updated_at = Utc::now();
+        self.repository.update_calendar(&calendar).await?;
+
+        let now = Utc::now();
+        for mut schedule in self.repository.list_schedules_on_calendar(tenant_id, calendar_id).await? {
+            self.sync_to_temporal(&schedule, Some(&calendar)).await?;
+            if schedule.state == ScheduleState::Active {
+                schedule.next_run_at = next_run(&schedule, Some(&calendar), now);
+                self.repository.update_schedule(&schedule).await?;
+            }
+        }
+
+        info!("Updated calendar {} for tenant {}", calendar_id, tenant_id);
+        Ok(calendar)
+    }
+
+    pub async fn delete_calendar(&self, tenant_id: &str, calendar_id: &str) -> WorkflowServiceResult<()> {
+        let in_use = self.repository.list_schedules_on_calendar(tenant_id, calendar_id).await?;
+        if !in_use.is_empty() {
+            return Err(WorkflowServiceError::Validation(format!(
+                "Calendar is used by {} schedule(s)",
+                in_use.len()
+            )));
+        }
+
+        if !self.repository.delete_calendar(tenant_id, calendar_id).await? {
+            return Err(WorkflowServiceError::NotFound(format!("Calendar {}", calendar_id)));
+        }
+        Ok(())
+    }
+
+    async fn find_schedule(&self, tenant_id: &str, schedule_id: &str) -> WorkflowServiceResult<WorkflowSchedule> {
+        self.repository
+            .get_schedule(tenant_id, schedule_id)
+            .await?
+            .ok_or_else(|| WorkflowServiceError::NotFound(format!("Schedule {}", schedule_id)))
+    }
+
+    async fn find_calendar(&self, tenant_id: &str, calendar_id: Option<&str>) -> WorkflowServiceResult<Option<WorkflowCalendar>> {
+        match calendar_id {
+            Some(calendar_id) => self
+                .repository
+                .get_calendar(tenant_id, calendar_id)
+                .await?
+                .map(Some)
+                .ok_or_else(|| WorkflowServiceError::Validation(format!("Unknown calendar {}", calendar_id))),
+            None => Ok(None),
+        }
+    }
+
+    async fn sync_to_temporal(&self, schedule: &WorkflowSchedule, calendar: Option<&WorkflowCalendar>) -> WorkflowServiceResult<()> {
+        let (spec, action) = temporal_schedule(schedule, calendar);
+        self.temporal
+            .update_schedule(&schedule.schedule_id, &spec, &action)
+            .await
+            .map_err(|e| WorkflowServiceError::Temporal(e.to_string()))
+    }
+
+    /// Check a schedule against the tenant's limits and return its next run
+    fn validate_schedule(
+        &self,
+        schedule: &WorkflowSchedule,
+        calendar: Option<&WorkflowCalendar>,
+        limits: &ScheduleLimits,
+        now: DateTime<Utc>,
+    ) -> WorkflowServiceResult<Option<DateTime<Utc>>> {
+        if !SCHEDULABLE_WORKFLOW_TYPES.contains(&schedule.workflow_type.as_str()) {
+            return Err(WorkflowServiceError::Validation(format!(
+                "Workflow type '{}' cannot be scheduled; expected one of {}",
+                schedule.workflow_type,
+                SCHEDULABLE_WORKFLOW_TYPES.join(", ")
+            )));
+        }
+        validate_input(&schedule.workflow_type, &schedule.input, &schedule.tenant_id)?;
+
+        if schedule.jitter_seconds > self.config.schedules.max_jitter_seconds {
+            return Err(WorkflowServiceError::Validation(format!(
+                "Jitter may be at most {} seconds",
+                self.config.schedules.max_jitter_seconds
+            )));
+        }
+
+        let cron = CronExpression::parse(&schedule.cron_expression)
+            .map_err(|e| WorkflowServiceError::Validation(format!("Invalid cron expression: {}", e)))?;
+        let timezone = parse_timezone(&schedule.timezone)?;
+
+        if let Some(interval) = cron.shortest_interval(now, timezone, INTERVAL_SAMPLES) {
+            if interval.num_seconds() < limits.min_interval_seconds as i64 {
+                return Err(WorkflowServiceError::LimitExceeded(format!(
+                    "Schedule runs every {} seconds; the minimum interval is {} seconds",
+                    interval.num_seconds(),
+                    limits.min_interval_seconds
+                )));
+            }
+        }
+
+        let excluded = calendar.map(|c| c.excluded_dates.as_slice()).unwrap_or(&[]);
+        let next = cron.next_after(now, timezone, excluded);
+        if next.is_none() {
+            return Err(WorkflowServiceError::Validation(
+                "Cron expression never matches a date outside the calendar's exclusions".to_string(),
+            ));
+        }
+
+        Ok(if schedule.state == ScheduleState::Active { next } else { None })
+    }
+
+    fn respond(&self, schedule: WorkflowSchedule, calendar: Option<&WorkflowCalendar>) -> ScheduleResponse {
+        let upcoming_runs = match (&schedule.state, CronExpression::parse(&schedule.cron_expression), schedule.timezone.parse::<Tz>()) {
+            (ScheduleState::Active, Ok(cron), Ok(timezone)) => {
+                let excluded = calendar.map(|c| c.excluded_dates.as_slice()).unwrap_or(&[]);
+                cron.upcoming(Utc::now(), timezone, excluded, UPCOMING_RUNS_SHOWN)
+            }
+            _ => Vec::new(),
+        };
+
+        ScheduleResponse { schedule, upcoming_runs }
+    }
+}
+
+fn parse_timezone(name: &str) -> WorkflowServiceResult<Tz> {
+    name.parse::<Tz>()
+        .map_err(|_| WorkflowServiceError::Validation(format!("Unknown timezone '{}'", name)))
+}
+
+fn normalize_dates(mut dates: Vec<NaiveDate>) -> Vec<NaiveDate> {
+    dates.sort();
+    dates.dedup();
+    dates
+}
+
+fn next_run(schedule: &WorkflowSchedule, calendar: Option<&WorkflowCalendar>, now: DateTime<Utc>) -> Option<DateTime<Utc>> {
+    let cron = CronExpression::parse(&schedule.cron_expression).ok()?;
+    let timezone = schedule.timezone.parse::<Tz>().ok()?;
+    let excluded = calendar.map(|c| c.excluded_dates.as_slice()).unwrap_or(&[]);
+    cron.next_after(now, timezone, excluded)
+}
+
+/// The scheduled input must be a valid request for the workflow and may only
+/// touch the tenant that owns the schedule
+fn validate_input(workflow_type: &str, input: &serde_json::Value, tenant_id: &str) -> WorkflowServiceResult<()> {
+    let invalid = |e: serde_json::Error| WorkflowServiceError::Validation(format!("Invalid input for {}: {}", workflow_type, e));
+
+    let tenants: Vec<String> = match workflow_type {
+        "data_migration_workflow" => {
+            let request: DataMigrationRequest = serde_json::from_value(input.clone()).map_err(invalid)?;
+            std::iter::once(request.target_tenant_id).chain(request.source_tenant_id).collect()
+        }
+        "bulk_operation_workflow" => {
+            let request: BulkOperationRequest = serde_json::from_value(input.clone()).map_err(invalid)?;
+            vec![request.tenant_id]
+        }
+        "compliance_workflow" => {
+            let request: ComplianceWorkflowRequest = serde_json::from_value(input.clone()).map_err(invalid)?;
+            vec![request.tenant_id]
+        }
+        "module_job_workflow" => {
+            let request: ModuleJobRequest = serde_json::from_value(input.clone()).map_err(invalid)?;
+            vec![request.tenant_id]
+        }
+        _ => Vec::new(),
+    };
+
+    if tenants.iter().any(|t| t != tenant_id) {
+        return Err(WorkflowServiceError::Authorization(
+            "Scheduled workflows may only act on the schedule's own tenant".to_string(),
+        ));
+    }
+    Ok(())
+}
+
+fn temporal_schedule(schedule: &WorkflowSchedule, calendar: Option<&WorkflowCalendar>) -> (ScheduleSpec, ScheduleAction) {
+    let spec = ScheduleSpec {
+        cron_expressions: vec![schedule.cron_expression.clone()],
+        timezone_name: schedule.timezone.clone(),
+        jitter: Duration::from_secs(schedule.jitter_seconds as u64),
+        excluded_dates: calendar.map(|c| c.excluded_dates.clone()).unwrap_or_default(),
+    };
+    let action = ScheduleAction {
+        workflow_type: schedule.workflow_type.clone(),
+        workflow_id: format!("{}_run", schedule.schedule_id),
+        task_queue: schedule.task_queue.clone(),
+        input: schedule.input.clone(),
+    };
+    (spec, action)
+}
+
+// Request/Response types
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateScheduleRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub workflow_type: String,
+    pub cron_expression: String,
+    /// Defaults to the calendar's timezone, then UTC
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub jitter_seconds: u32,
+    pub calendar_id: Option<String>,
+    pub input: serde_json::Value,
+    #[serde(default)]
+    pub start_paused: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateScheduleRequest {
+    pub description: Option<String>,
+    pub cron_expression: String,
+    /// Keeps the current timezone when omitted
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub jitter_seconds: u32,
+    pub calendar_id: Option<String>,
+    pub input: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PauseScheduleRequest {
+    pub reason: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ScheduleResponse {
+    #[serde(flatten)]
+    pub schedule: WorkflowSchedule,
+    pub upcoming_runs: Vec<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListSchedulesResponse {
+    pub schedules: Vec<WorkflowSchedule>,
+    pub total_count: u32,
+    pub limits: ScheduleLimits,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CreateCalendarRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub excluded_dates: Vec<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UpdateCalendarRequest {
+    pub description: Option<String>,
+    pub timezone: Option<String>,
+    #[serde(default)]
+    pub excluded_dates: Vec<NaiveDate>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetScheduleLimitsRequest {
+    pub max_schedules: u32,
+    pub min_interval_seconds: u32,
+}
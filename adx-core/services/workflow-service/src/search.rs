@@ -0,0 +1,165 @@
+//! Workflow listing over Temporal's visibility store.
+//!
+//! Every workflow the service starts on Temporal carries the tenant, the
+//! initiating user and its business keys as search attributes, so a tenant's
+//! runs can be filtered by status, type, initiator or business key without
+//! reading their histories.
+
+use crate::{
+    error::{WorkflowServiceError, WorkflowServiceResult},
+    visualization::RunStatus,
+};
+use adx_shared::temporal::{
+    client::WorkflowStatus, AdxTemporalClient, WorkflowExecutionSummary, WorkflowListFilter, MAX_LIST_PAGE_SIZE,
+};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+
+const DEFAULT_PAGE_SIZE: u32 = 50;
+
+#[derive(Debug, Deserialize)]
+pub struct ListWorkflowsParams {
+    pub status: Option<RunStatus>,
+    #[serde(rename = "type")]
+    pub workflow_type: Option<String>,
+    /// User who started the runs
+    pub initiator: Option<String>,
+    pub business_key: Option<String>,
+    pub page_size: Option<u32>,
+    /// `next_page_token` of the previous page
+    pub page_token: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct WorkflowSummary {
+    pub workflow_id: String,
+    pub run_id: String,
+    pub workflow_type: String,
+    pub status: RunStatus,
+    pub task_queue: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub user_id: Option<String>,
+    pub business_keys: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct ListWorkflowsResponse {
+    /// Newest first
+    pub workflows: Vec<WorkflowSummary>,
+    /// Runs matching the filter across all pages
+    pub total_count: u64,
+    pub page_size: u32,
+    pub next_page_token: Option<String>,
+    pub has_more: bool,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct InFlightParams {
+    /// Only this tenant's runs; all tenants' otherwise
+    pub tenant_id: Option<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct InFlightWorkflows {
+    pub tenant_id: Option<String>,
+    pub running: u64,
+    pub counted_at: DateTime<Utc>,
+}
+
+pub struct WorkflowSearch {
+    temporal: AdxTemporalClient,
+}
+
+impl WorkflowSearch {
+    pub fn new(temporal: AdxTemporalClient) -> Self {
+        Self { temporal }
+    }
+
+    pub async fn list(&self, tenant_id: &str, params: ListWorkflowsParams) -> WorkflowServiceResult<ListWorkflowsResponse> {
+        let page_size = params.page_size.unwrap_or(DEFAULT_PAGE_SIZE).clamp(1, MAX_LIST_PAGE_SIZE);
+        let filter = WorkflowListFilter {
+            tenant_id: tenant_id.to_string(),
+            status: params.status.map(temporal_status),
+            workflow_type: non_empty(params.workflow_type),
+            user_id: non_empty(params.initiator),
+            business_key: non_empty(params.business_key),
+        };
+
+        let page = self
+            .temporal
+            .list_workflow_executions(&filter, page_size, params.page_token.as_deref())
+            .await
+            .map_err(|e| WorkflowServiceError::Temporal(e.to_string()))?;
+        let total_count = self
+            .temporal
+            .count_workflow_executions(&filter)
+            .await
+            .map_err(|e| WorkflowServiceError::Temporal(e.to_string()))?;
+
+        Ok(ListWorkflowsResponse {
+            workflows: page.executions.into_iter().map(summary).collect(),
+            total_count,
+            page_size,
+            has_more: page.next_page_token.is_some(),
+            next_page_token: page.next_page_token,
+        })
+    }
+
+    /// Runs still going, which the gateway waits on while it drains traffic
+    /// ahead of a maintenance window
+    pub async fn in_flight(&self, params: InFlightParams) -> WorkflowServiceResult<InFlightWorkflows> {
+        let tenant_id = non_empty(params.tenant_id);
+        let running = self
+            .temporal
+            .count_running_workflows(tenant_id.as_deref())
+            .await
+            .map_err(|e| WorkflowServiceError::Temporal(e.to_string()))?;
+
+        Ok(InFlightWorkflows {
+            tenant_id,
+            running,
+            counted_at: Utc::now(),
+        })
+    }
+}
+
+fn non_empty(value: Option<String>) -> Option<String> {
+    value.map(|v| v.trim().to_string()).filter(|v| !v.is_empty())
+}
+
+fn temporal_status(status: RunStatus) -> WorkflowStatus {
+    match status {
+        RunStatus::Running => WorkflowStatus::Running,
+        RunStatus::Completed => WorkflowStatus::Completed,
+        RunStatus::Failed => WorkflowStatus::Failed,
+        RunStatus::Canceled => WorkflowStatus::Cancelled,
+        RunStatus::Terminated => WorkflowStatus::Terminated,
+        RunStatus::TimedOut => WorkflowStatus::TimedOut,
+        RunStatus::ContinuedAsNew => WorkflowStatus::ContinuedAsNew,
+    }
+}
+
+fn summary(execution: WorkflowExecutionSummary) -> WorkflowSummary {
+    let status = match execution.status {
+        WorkflowStatus::Running => RunStatus::Running,
+        WorkflowStatus::Completed => RunStatus::Completed,
+        WorkflowStatus::Failed => RunStatus::Failed,
+        WorkflowStatus::Cancelled => RunStatus::Canceled,
+        WorkflowStatus::Terminated => RunStatus::Terminated,
+        WorkflowStatus::TimedOut => RunStatus::TimedOut,
+        WorkflowStatus::ContinuedAsNew => RunStatus::ContinuedAsNew,
+    };
+
+    WorkflowSummary {
+        workflow_id: execution.workflow_id,
+        run_id: execution.run_id,
+        workflow_type: execution.workflow_type,
+        status,
+        task_queue: execution.task_queue,
+        started_at: execution.start_time,
+        closed_at: execution.close_time,
+        user_id: execution.search_attributes.user_id,
+        business_keys: execution.search_attributes.business_keys,
+    }
+}
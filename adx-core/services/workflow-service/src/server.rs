@@ -1,8 +1,28 @@
 use crate::{
+    approvals::{ApprovalManager, ApprovalSignalRegistry},
+    batches::WorkflowBatchManager,
     config::WorkflowServiceConfig,
+    controls::{RunControlRegistry, WorkflowControlManager},
+    dead_letters::DeadLetterQueue,
     error::{WorkflowServiceError, WorkflowServiceResult},
     handlers::*,
+    interactions::{InteractionRegistry, WorkflowInteractionManager},
+    monitoring::{AlertManager, CostTracker, SlaMonitor},
+    repositories::{
+        PostgresWorkflowApprovalRepository, PostgresWorkflowBatchRepository, PostgresWorkflowControlRepository,
+        PostgresWorkflowCostRepository, PostgresWorkflowDeadLetterRepository, PostgresWorkflowScheduleRepository,
+        PostgresWorkflowSlaRepository, PostgresWorkflowTaskQueueRepository, PostgresWorkflowTemplateRepository,
+        PostgresWorkflowTriggerRepository, PostgresWorkflowVersionRepository,
+    },
+    schedules::WorkflowScheduleManager,
+    search::WorkflowSearch,
+    task_queues::TaskQueueRouter,
+    templates::WorkflowTemplateManager,
+    triggers::WorkflowTriggerManager,
+    versioning::WorkflowVersionManager,
+    visualization::ExecutionHistoryManager,
 };
+use adx_shared::temporal::{AdxTemporalClient, PriorityLanes};
 use axum::{
     extract::Extension,
     http::{header, Method, StatusCode},
@@ -12,6 +32,7 @@ use axum::{
     Router,
 };
 use serde_json::json;
+use sqlx::PgPool;
 use std::{net::SocketAddr, sync::Arc, time::Duration};
 use tower::ServiceBuilder;
 use tower_http::{
@@ -19,7 +40,7 @@ use tower_http::{
     timeout::TimeoutLayer,
     trace::TraceLayer,
 };
-use tracing::info;
+use tracing::{info, warn};
 
 pub struct WorkflowServer {
     config: WorkflowServiceConfig,
@@ -27,10 +48,17 @@ pub struct WorkflowServer {
 }
 
 impl WorkflowServer {
-    pub fn new(config: WorkflowServiceConfig) -> Self {
-        let app = create_app(config.clone());
+    pub async fn new(config: WorkflowServiceConfig, pool: PgPool) -> WorkflowServiceResult<Self> {
+        let temporal = AdxTemporalClient::new(config.temporal.client_config())
+            .await
+            .map_err(|e| WorkflowServiceError::Temporal(e.to_string()))?;
+        // Not fatal: only workflow listing depends on the attributes
+        if let Err(e) = temporal.register_search_attributes().await {
+            warn!("Failed to register workflow search attributes: {}", e);
+        }
+        let app = create_app(config.clone(), pool, temporal);
         
-        Self { config, app }
+        Ok(Self { config, app })
     }
 
     pub async fn run(self) -> WorkflowServiceResult<()> {
@@ -49,8 +77,77 @@ impl WorkflowServer {
     }
 }
 
-fn create_app(config: WorkflowServiceConfig) -> Router {
+fn create_app(config: WorkflowServiceConfig, pool: PgPool, temporal: AdxTemporalClient) -> Router {
     let config = Arc::new(config);
+    let alerts = Arc::new(AlertManager::new());
+    let task_queues = Arc::new(TaskQueueRouter::new(
+        config.clone(),
+        Arc::new(PostgresWorkflowTaskQueueRepository::new(pool.clone())),
+    ));
+    let schedules = Arc::new(WorkflowScheduleManager::new(
+        config.clone(),
+        Arc::new(PostgresWorkflowScheduleRepository::new(pool.clone())),
+        temporal.clone(),
+        task_queues.clone(),
+    ));
+    let templates = Arc::new(WorkflowTemplateManager::new(
+        config.clone(),
+        Arc::new(PostgresWorkflowTemplateRepository::new(pool.clone())),
+        temporal.clone(),
+        task_queues.clone(),
+    ));
+    let costs = Arc::new(CostTracker::new(
+        config.clone(),
+        Arc::new(PostgresWorkflowCostRepository::new(pool.clone())),
+        alerts.clone(),
+    ));
+    let approvals = Arc::new(ApprovalManager::new(
+        Arc::new(PostgresWorkflowApprovalRepository::new(pool.clone())),
+        temporal.clone(),
+        Arc::new(ApprovalSignalRegistry::new()),
+    ));
+    let dead_letters = Arc::new(DeadLetterQueue::new(
+        Arc::new(PostgresWorkflowDeadLetterRepository::new(pool.clone())),
+        temporal.clone(),
+        alerts.clone(),
+        task_queues.clone(),
+        costs.clone(),
+    ));
+    let slas = Arc::new(SlaMonitor::new(
+        Arc::new(PostgresWorkflowSlaRepository::new(pool.clone())),
+        temporal.clone(),
+        alerts,
+    ));
+    let runs = Arc::new(RunControlRegistry::new(PriorityLanes::new(config.task_queues.batch_max_yield())));
+    let controls = Arc::new(WorkflowControlManager::new(
+        Arc::new(PostgresWorkflowControlRepository::new(pool.clone())),
+        temporal.clone(),
+        runs.clone(),
+    ));
+    let batches = Arc::new(WorkflowBatchManager::new(
+        config.clone(),
+        Arc::new(PostgresWorkflowBatchRepository::new(pool.clone())),
+        costs.clone(),
+        slas.clone(),
+        dead_letters.clone(),
+        task_queues.clone(),
+        runs.clone(),
+    ));
+    let triggers = Arc::new(WorkflowTriggerManager::new(
+        config.clone(),
+        Arc::new(PostgresWorkflowTriggerRepository::new(pool.clone())),
+        temporal.clone(),
+        task_queues.clone(),
+        costs.clone(),
+    ));
+    triggers.spawn_dispatcher();
+    let versions = Arc::new(WorkflowVersionManager::new(
+        Arc::new(PostgresWorkflowVersionRepository::new(pool)),
+        temporal.clone(),
+    ));
+    let history = Arc::new(ExecutionHistoryManager::new(temporal.clone()));
+    let search = Arc::new(WorkflowSearch::new(temporal.clone()));
+    let interactions = Arc::new(WorkflowInteractionManager::new(temporal, InteractionRegistry::builtin()));
 
     Router::new()
         // Health check endpoint
@@ -63,11 +160,17 @@ fn create_app(config: WorkflowServiceConfig) -> Router {
         .route("/api/v1/workflows/data-migration", post(start_data_migration_workflow))
         .route("/api/v1/workflows/bulk-operation", post(start_bulk_operation_workflow))
         .route("/api/v1/workflows/compliance", post(start_compliance_workflow))
+        .route("/api/v1/workflows/business-transaction", post(start_business_transaction_workflow))
         
         // Workflow status endpoints
         .route("/api/v1/workflows/:workflow_id/status", get(get_workflow_status))
         .route("/api/v1/workflows/:workflow_id/status/detailed", get(get_workflow_status_detailed))
         .route("/api/v1/workflows/:workflow_id/debug", get(get_workflow_debug_info))
+        .route("/api/v1/workflows/:workflow_id/graph", get(get_workflow_execution_graph))
+        .route("/api/v1/workflows/:workflow_id/history/export", get(export_workflow_execution_history))
+        .route("/api/v1/workflows/:workflow_id/interactions", get(get_workflow_interactions))
+        .route("/api/v1/workflows/:workflow_id/signal/:signal_name", post(signal_workflow))
+        .route("/api/v1/workflows/:workflow_id/query/:query_name", post(query_workflow))
         .route("/api/v1/workflows/:workflow_id/cancel", post(cancel_workflow))
         .route("/api/v1/workflows/:workflow_id/retry", post(retry_workflow))
         
@@ -76,6 +179,7 @@ fn create_app(config: WorkflowServiceConfig) -> Router {
         .route("/api/v1/workflows/:workflow_id/retry-enhanced", post(retry_workflow_enhanced))
         .route("/api/v1/workflows/:workflow_id/pause", post(pause_workflow))
         .route("/api/v1/workflows/:workflow_id/resume", post(resume_workflow))
+        .route("/api/v1/workflows/:workflow_id/controls", get(get_workflow_controls))
         .route("/api/v1/workflows/:workflow_id/terminate", post(terminate_workflow))
         .route("/api/v1/workflows/:workflow_id/management-options", get(get_workflow_management_options))
         .route("/api/v1/workflows/bulk-operation", post(bulk_workflow_operation))
@@ -85,11 +189,27 @@ fn create_app(config: WorkflowServiceConfig) -> Router {
         .route("/api/v1/workflows/history", get(get_workflow_history))
         .route("/api/v1/workflows/analytics", get(get_workflow_analytics))
         .route("/api/v1/workflows/health", get(get_workflow_health_report))
+        .route("/api/v1/workflows/:workflow_id/cost", get(get_workflow_run_cost))
+        .route("/api/v1/admin/workflows/in-flight", get(get_in_flight_workflows))
+        
+        // Workflow costs and budgets
+        .route("/api/v1/workflow-costs", get(get_workflow_cost_report))
+        .route("/api/v1/workflow-budget", get(get_workflow_budget))
+        .route("/api/v1/admin/tenants/:tenant_id/workflow-budget", put(set_workflow_budget))
+        
+        // Workflow SLAs
+        .route("/api/v1/workflow-slas", get(list_workflow_slas))
+        .route("/api/v1/workflow-slas/status", get(get_workflow_sla_status))
+        .route("/api/v1/workflow-slas/breaches", get(list_workflow_sla_breaches))
+        .route("/api/v1/workflow-slas/:workflow_type", get(get_workflow_sla))
+        .route("/api/v1/workflow-slas/:workflow_type", put(set_workflow_sla))
+        .route("/api/v1/workflow-slas/:workflow_type", delete(delete_workflow_sla))
         
         // Workflow versioning endpoints
         .route("/api/v1/workflow-versions/register", post(register_workflow_version))
         .route("/api/v1/workflow-versions/:workflow_type", get(get_workflow_versions))
         .route("/api/v1/workflow-versions/:workflow_type/compatibility", get(get_compatibility_matrix))
+        .route("/api/v1/workflow-versions/:workflow_type/outdated", get(get_outdated_workflow_executions))
         .route("/api/v1/workflow-versions/migrate", post(migrate_workflows))
         .route("/api/v1/workflow-versions/migrations/:migration_id/status", get(get_migration_status))
         .route("/api/v1/workflow-versions/migrations/rollback", post(rollback_migration))
@@ -102,10 +222,60 @@ fn create_app(config: WorkflowServiceConfig) -> Router {
         .route("/api/v1/workflow-templates/:template_id", put(update_workflow_template))
         .route("/api/v1/workflow-templates/:template_id", delete(delete_workflow_template))
         .route("/api/v1/workflow-templates/:template_id/usage", get(get_template_usage))
-        .route("/api/v1/workflow-templates/create-from", post(create_workflow_from_template))
+        .route("/api/v1/workflow-templates/:template_id/instantiate", post(instantiate_workflow_template))
+        .route("/api/v1/workflow-templates/validate", post(validate_workflow_template))
         .route("/api/v1/workflow-templates/generate", post(generate_template_from_workflows))
         .route("/api/v1/workflow-templates/analyze-patterns", get(analyze_workflow_patterns))
         
+        // Workflow schedule endpoints
+        .route("/api/v1/workflow-schedules", post(create_workflow_schedule))
+        .route("/api/v1/workflow-schedules", get(list_workflow_schedules))
+        .route("/api/v1/workflow-schedules/:schedule_id", get(get_workflow_schedule))
+        .route("/api/v1/workflow-schedules/:schedule_id", put(update_workflow_schedule))
+        .route("/api/v1/workflow-schedules/:schedule_id", delete(delete_workflow_schedule))
+        .route("/api/v1/workflow-schedules/:schedule_id/pause", post(pause_workflow_schedule))
+        .route("/api/v1/workflow-schedules/:schedule_id/resume", post(resume_workflow_schedule))
+        .route("/api/v1/workflow-schedule-limits", get(get_workflow_schedule_limits))
+        .route("/api/v1/admin/tenants/:tenant_id/workflow-schedule-limits", put(set_workflow_schedule_limits))
+        .route("/api/v1/workflow-task-queues", get(get_workflow_task_queues))
+        .route("/api/v1/admin/tenants/:tenant_id/workflow-task-queues", put(set_workflow_task_queues))
+        .route("/api/v1/admin/tenants/:tenant_id/workflow-task-queues", delete(clear_workflow_task_queues))
+        .route("/api/v1/workflow-calendars", post(create_workflow_calendar))
+        .route("/api/v1/workflow-calendars", get(list_workflow_calendars))
+        .route("/api/v1/workflow-calendars/:calendar_id", put(update_workflow_calendar))
+        .route("/api/v1/workflow-calendars/:calendar_id", delete(delete_workflow_calendar))
+        
+        // Approval inbox
+        .route("/api/v1/approvals", get(list_approvals))
+        .route("/api/v1/approvals/:approval_id", get(get_approval))
+        .route("/api/v1/approvals/:approval_id/approve", post(approve_approval))
+        .route("/api/v1/approvals/:approval_id/reject", post(reject_approval))
+        
+        // Dead-letter queue
+        .route("/api/v1/dead-letters", get(list_dead_letters))
+        .route("/api/v1/dead-letters/bulk-resubmit", post(bulk_resubmit_dead_letters))
+        .route("/api/v1/dead-letters/:dead_letter_id", get(get_dead_letter))
+        .route("/api/v1/dead-letters/:dead_letter_id/input", put(update_dead_letter_input))
+        .route("/api/v1/dead-letters/:dead_letter_id/resubmit", post(resubmit_dead_letter))
+        .route("/api/v1/dead-letters/:dead_letter_id/discard", post(discard_dead_letter))
+        
+        // Batch launcher
+        .route("/api/v1/workflow-batches", post(submit_workflow_batch))
+        .route("/api/v1/workflow-batches/:batch_id", get(get_workflow_batch_status))
+        .route("/api/v1/workflow-batches/:batch_id/children", get(list_workflow_batch_children))
+        .route("/api/v1/workflow-batches/:batch_id/cancel", post(cancel_workflow_batch))
+        
+        // Event triggers
+        .route("/api/v1/workflow-triggers", get(list_workflow_triggers))
+        .route("/api/v1/workflow-triggers", post(create_workflow_trigger))
+        .route("/api/v1/workflow-triggers/:trigger_id", get(get_workflow_trigger))
+        .route("/api/v1/workflow-triggers/:trigger_id", put(update_workflow_trigger))
+        .route("/api/v1/workflow-triggers/:trigger_id", delete(delete_workflow_trigger))
+        .route("/api/v1/workflow-triggers/:trigger_id/firings", get(list_workflow_trigger_firings))
+        .route("/api/v1/workflow-events", post(publish_workflow_event))
+        .route("/api/v1/workflow-events/webhooks/:source", post(receive_workflow_webhook))
+        .route("/api/v1/workflow-events/:event_id", get(get_workflow_event))
+        
         // Service coordination endpoints
         .route("/api/v1/coordination/health-check", post(coordinate_health_check))
         .route("/api/v1/coordination/backup", post(create_cross_service_backup))
@@ -113,6 +283,21 @@ fn create_app(config: WorkflowServiceConfig) -> Router {
         
         // Add middleware
         .layer(Extension(config))
+        .layer(Extension(schedules))
+        .layer(Extension(templates))
+        .layer(Extension(approvals))
+        .layer(Extension(dead_letters))
+        .layer(Extension(history))
+        .layer(Extension(search))
+        .layer(Extension(interactions))
+        .layer(Extension(task_queues))
+        .layer(Extension(costs))
+        .layer(Extension(slas))
+        .layer(Extension(batches))
+        .layer(Extension(triggers))
+        .layer(Extension(versions))
+        .layer(Extension(runs))
+        .layer(Extension(controls))
         .layer(middleware::from_fn(tenant_context_middleware))
 }
 
@@ -178,6 +363,23 @@ async fn check_service_dependencies(services: &crate::config::ServiceEndpoints)
     results.iter().all(|r| r.as_ref().map(|resp| resp.status().is_success()).unwrap_or(false))
 }
 
+const TENANT_SCOPED_PREFIXES: [&str; 14] = [
+    "/api/v1/workflows",
+    "/api/v1/workflow-templates",
+    "/api/v1/workflow-schedules",
+    "/api/v1/workflow-schedule-limits",
+    "/api/v1/workflow-task-queues",
+    "/api/v1/workflow-costs",
+    "/api/v1/workflow-budget",
+    "/api/v1/workflow-slas",
+    "/api/v1/workflow-batches",
+    "/api/v1/workflow-calendars",
+    "/api/v1/approvals",
+    "/api/v1/dead-letters",
+    "/api/v1/workflow-triggers",
+    "/api/v1/workflow-events",
+];
+
 async fn tenant_context_middleware(
     req: axum::extract::Request,
     next: axum::middleware::Next,
@@ -196,15 +398,29 @@ async fn tenant_context_middleware(
         .and_then(|h| h.to_str().ok())
         .map(|s| s.to_string());
     
-    // For workflow endpoints, tenant_id is required
-    if req.uri().path().starts_with("/api/v1/workflows") && tenant_id.is_none() {
+    // Extract the user's roles, comma separated, used to match approval assignees
+    let roles = req
+        .headers()
+        .get("X-User-Roles")
+        .and_then(|h| h.to_str().ok())
+        .map(|s| {
+            s.split(',')
+                .map(|role| role.trim().to_string())
+                .filter(|role| !role.is_empty())
+                .collect()
+        })
+        .unwrap_or_default();
+    
+    // For workflow, template, schedule, calendar and approval endpoints, tenant_id is required
+    let path = req.uri().path();
+    if TENANT_SCOPED_PREFIXES.iter().any(|prefix| path.starts_with(prefix)) && tenant_id.is_none() {
         return Err(StatusCode::BAD_REQUEST);
     }
     
     // Add context to request extensions
     let mut req = req;
     if let Some(tenant_id) = tenant_id {
-        req.extensions_mut().insert(TenantContext { tenant_id, user_id });
+        req.extensions_mut().insert(TenantContext { tenant_id, user_id, roles });
     }
     
     Ok(next.run(req).await)
@@ -214,4 +430,5 @@ async fn tenant_context_middleware(
 pub struct TenantContext {
     pub tenant_id: String,
     pub user_id: Option<String>,
+    pub roles: Vec<String>,
 }
\ No newline at end of file
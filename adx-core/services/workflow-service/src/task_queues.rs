@@ -0,0 +1,170 @@
+//! Task queue routing.
+//!
+//! Runs are spread over Temporal task queues so one tenant's batch jobs
+//! can't starve everyone else's interactive workflows. Batch workflow types,
+//! and runs started at low priority, go to a batch queue next to each
+//! interactive queue; batch queues are polled by their own worker pool,
+//! which may only use a share of the task slots. Tenants without an assignment share the
+//! default queues; an operator can move a tenant onto its subscription
+//! tier's queues or onto queues of its own.
+
+use crate::{
+    config::WorkflowServiceConfig,
+    error::{WorkflowServiceError, WorkflowServiceResult},
+    repositories::WorkflowTaskQueueRepository,
+};
+use adx_shared::temporal::{PriorityLane, WorkflowPriority};
+use serde::{Deserialize, Serialize};
+use std::sync::Arc;
+use tracing::info;
+
+/// A tenant's queues: those of a subscription tier, or a dedicated queue.
+/// Exactly one is set.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TaskQueueAssignment {
+    pub tier: Option<String>,
+    pub dedicated_queue: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SetTaskQueueAssignmentRequest {
+    pub tier: Option<String>,
+    pub dedicated_queue: Option<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TaskQueueRoutingResponse {
+    pub tenant_id: String,
+    /// None when the tenant uses the default queues
+    pub assignment: Option<TaskQueueAssignment>,
+    pub interactive_queue: String,
+    pub batch_queue: String,
+}
+
+pub struct TaskQueueRouter {
+    config: Arc<WorkflowServiceConfig>,
+    repository: Arc<dyn WorkflowTaskQueueRepository>,
+}
+
+impl TaskQueueRouter {
+    pub fn new(config: Arc<WorkflowServiceConfig>, repository: Arc<dyn WorkflowTaskQueueRepository>) -> Self {
+        Self { config, repository }
+    }
+
+    /// Lane a run of `workflow_type` runs in. A caller may move a run to
+    /// the batch lane with a low priority but can't raise a batch workflow
+    /// type to interactive.
+    pub fn lane(&self, workflow_type: &str, priority: Option<WorkflowPriority>) -> PriorityLane {
+        let configured = if self.config.task_queues.is_batch(workflow_type) {
+            PriorityLane::Batch
+        } else {
+            PriorityLane::Interactive
+        };
+        priority.map_or(configured, |priority| configured.min(priority.lane()))
+    }
+
+    /// Queue a new run of `workflow_type` for the tenant should start on
+    pub async fn route(&self, tenant_id: &str, workflow_type: &str) -> WorkflowServiceResult<String> {
+        let assignment = self.repository.get_assignment(tenant_id).await?;
+        Ok(resolve_queue(&self.config, assignment.as_ref(), self.lane(workflow_type, None)))
+    }
+
+    /// Queue for a run when the tenant's assignment can't be looked up
+    pub fn default_queue(&self, workflow_type: &str) -> String {
+        resolve_queue(&self.config, None, self.lane(workflow_type, None))
+    }
+
+    pub async fn get_routing(&self, tenant_id: &str) -> WorkflowServiceResult<TaskQueueRoutingResponse> {
+        let assignment = self.repository.get_assignment(tenant_id).await?;
+        Ok(routing_response(&self.config, tenant_id, assignment))
+    }
+
+    /// Move a tenant onto a tier's queues or a dedicated queue. Runs already
+    /// started stay on the queue they were started on.
+    pub async fn set_assignment(
+        &self,
+        tenant_id: &str,
+        request: SetTaskQueueAssignmentRequest,
+    ) -> WorkflowServiceResult<TaskQueueRoutingResponse> {
+        let queues = &self.config.task_queues;
+        let assignment = match (request.tier, request.dedicated_queue) {
+            (Some(tier), None) => {
+                if !queues.tier_queues.contains_key(&tier) {
+                    return Err(WorkflowServiceError::Validation(format!(
+                        "Tier '{}' has no task queues configured",
+                        tier
+                    )));
+                }
+                TaskQueueAssignment { tier: Some(tier), dedicated_queue: None }
+            }
+            (None, Some(queue)) => {
+                if !queues.dedicated_queues.contains(&queue) {
+                    return Err(WorkflowServiceError::Validation(format!(
+                        "'{}' is not a configured dedicated task queue",
+                        queue
+                    )));
+                }
+                TaskQueueAssignment { tier: None, dedicated_queue: Some(queue) }
+            }
+            _ => {
+                return Err(WorkflowServiceError::Validation(
+                    "Exactly one of tier or dedicated_queue is required".to_string(),
+                ))
+            }
+        };
+
+        self.repository.set_assignment(tenant_id, &assignment).await?;
+        info!("Assigned task queues for tenant {}: {:?}", tenant_id, assignment);
+
+        Ok(routing_response(&self.config, tenant_id, Some(assignment)))
+    }
+
+    /// Put a tenant back on the default queues
+    pub async fn clear_assignment(&self, tenant_id: &str) -> WorkflowServiceResult<TaskQueueRoutingResponse> {
+        if !self.repository.clear_assignment(tenant_id).await? {
+            return Err(WorkflowServiceError::NotFound(format!(
+                "Tenant {} has no task queue assignment",
+                tenant_id
+            )));
+        }
+        info!("Cleared task queue assignment for tenant {}", tenant_id);
+
+        Ok(routing_response(&self.config, tenant_id, None))
+    }
+}
+
+/// Interactive queue for an assignment, then its batch queue for batch
+/// work. Assignments that no longer match the configuration fall back to
+/// the default queues.
+pub fn resolve_queue(
+    config: &WorkflowServiceConfig,
+    assignment: Option<&TaskQueueAssignment>,
+    lane: PriorityLane,
+) -> String {
+    let queues = &config.task_queues;
+    let interactive = assignment
+        .and_then(|assignment| match (&assignment.tier, &assignment.dedicated_queue) {
+            (_, Some(queue)) if queues.dedicated_queues.contains(queue) => Some(queue.clone()),
+            (Some(tier), _) => queues.tier_queues.get(tier).cloned(),
+            _ => None,
+        })
+        .unwrap_or_else(|| config.temporal.task_queue.clone());
+
+    match lane {
+        PriorityLane::Interactive => interactive,
+        PriorityLane::Batch => queues.batch_queue(&interactive),
+    }
+}
+
+fn routing_response(
+    config: &WorkflowServiceConfig,
+    tenant_id: &str,
+    assignment: Option<TaskQueueAssignment>,
+) -> TaskQueueRoutingResponse {
+    TaskQueueRoutingResponse {
+        tenant_id: tenant_id.to_string(),
+        interactive_queue: resolve_queue(config, assignment.as_ref(), PriorityLane::Interactive),
+        batch_queue: resolve_queue(config, assignment.as_ref(), PriorityLane::Batch),
+        assignment,
+    }
+}
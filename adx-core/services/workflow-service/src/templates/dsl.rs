@@ -0,0 +1,908 @@
+//! The workflow template DSL.
+//!
+//! A template is a JSON or YAML document naming the parameters it takes and
+//! the steps it runs. Each step calls one cross-service activity, runs one of
+//! the built-in workflows or waits for a human approval, may depend on
+//! earlier steps, and builds its input
+//! from references of the form `${params.name}`, `${steps.<step_id>.output}`
+//! (optionally followed by a dotted path into the output) and
+//! `${context.tenant_id}`, `${context.user_id}` or `${context.workflow_id}`.
+//! A string that is exactly one reference takes the referenced value with its
+//! type; references embedded in longer strings are interpolated as text.
+
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use super::engine::{ACTIVITY_TYPES, SUB_WORKFLOW_TYPES};
+use super::ValidationResults;
+use crate::approvals::ApprovalPolicy;
+use crate::error::{WorkflowServiceError, WorkflowServiceResult};
+
+const MAX_STEPS: usize = 100;
+const MAX_RETRY_ATTEMPTS: u32 = 10;
+const MAX_STEP_TIMEOUT_SECONDS: u64 = 24 * 60 * 60;
+
+/// A template as authored: catalogue metadata plus the definition
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateDocument {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(default)]
+    pub category: String,
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Other tenants may instantiate public templates but not change them
+    #[serde(default)]
+    pub is_public: bool,
+    #[serde(flatten)]
+    pub definition: TemplateDefinition,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum DocumentFormat {
+    Json,
+    Yaml,
+}
+
+impl DocumentFormat {
+    /// YAML for `application/yaml`, `application/x-yaml` and `text/yaml`;
+    /// JSON otherwise
+    pub fn from_content_type(content_type: Option<&str>) -> Self {
+        match content_type {
+            Some(content_type) if content_type.to_ascii_lowercase().contains("yaml") => Self::Yaml,
+            _ => Self::Json,
+        }
+    }
+}
+
+pub fn parse_document(body: &[u8], format: DocumentFormat) -> WorkflowServiceResult<TemplateDocument> {
+    match format {
+        DocumentFormat::Json => serde_json::from_slice(body)
+            .map_err(|e| WorkflowServiceError::InvalidTemplate(format!("Invalid JSON template: {}", e))),
+        DocumentFormat::Yaml => serde_yaml::from_slice(body)
+            .map_err(|e| WorkflowServiceError::InvalidTemplate(format!("Invalid YAML template: {}", e))),
+    }
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TemplateDefinition {
+    #[serde(default)]
+    pub parameters: Vec<TemplateParameter>,
+    pub steps: Vec<TemplateStep>,
+    #[serde(default)]
+    pub outputs: Vec<TemplateOutput>,
+    #[serde(default)]
+    pub error_handling: ErrorHandling,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TemplateStep {
+    pub step_id: String,
+    #[serde(default)]
+    pub step_type: StepType,
+    #[serde(default)]
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Activity to call when `step_type` is `activity`
+    #[serde(default)]
+    pub activity_type: Option<String>,
+    /// Workflow to run when `step_type` is `sub_workflow`
+    #[serde(default)]
+    pub workflow_type: Option<String>,
+    /// Who decides and for how long when `step_type` is `approval`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub approval: Option<ApprovalPolicy>,
+    /// Input of the activity or workflow; values may contain references.
+    /// For approval steps these are shown to the assignees.
+    #[serde(default)]
+    pub parameters: Map<String, Value>,
+    #[serde(default)]
+    pub depends_on: Vec<String>,
+    /// A single reference; the step is skipped when it resolves to null,
+    /// false, zero or an empty string, array or object
+    #[serde(default)]
+    pub when: Option<String>,
+    #[serde(default)]
+    pub timeout_seconds: Option<u64>,
+    /// Overrides `error_handling.default_retry_policy`
+    #[serde(default)]
+    pub retry_policy: Option<RetryPolicy>,
+    /// A failed step is recorded and its dependents skipped, but the
+    /// workflow carries on
+    #[serde(default)]
+    pub continue_on_error: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum StepType {
+    #[default]
+    Activity,
+    SubWorkflow,
+    Approval,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TemplateParameter {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    #[serde(rename = "type", default)]
+    pub parameter_type: ParameterType,
+    #[serde(default)]
+    pub required: bool,
+    #[serde(default)]
+    pub default_value: Option<Value>,
+    /// `email_format`, `uuid_format`, `non_empty`, `min_length:N`,
+    /// `max_length:N`, `min:N`, `max:N` or `one_of:a|b|c`
+    #[serde(default)]
+    pub validation_rules: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, Copy, PartialEq, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum ParameterType {
+    String,
+    Number,
+    Integer,
+    Boolean,
+    Array,
+    Object,
+    #[default]
+    Any,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct TemplateOutput {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Reference the output is read from, e.g. `${steps.create_account.output.user_id}`
+    pub from: String,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq, Default)]
+pub struct ErrorHandling {
+    #[serde(default)]
+    pub default_retry_policy: RetryPolicy,
+    /// Steps run, in the order listed, when a step fails without
+    /// `continue_on_error`. They are not part of the normal run.
+    #[serde(default)]
+    pub compensation_steps: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone, PartialEq)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    #[serde(default = "default_initial_delay_seconds")]
+    pub initial_delay_seconds: u64,
+    #[serde(default = "default_backoff_multiplier")]
+    pub backoff_multiplier: f64,
+    #[serde(default)]
+    pub maximum_delay_seconds: Option<u64>,
+}
+
+fn default_initial_delay_seconds() -> u64 {
+    1
+}
+
+fn default_backoff_multiplier() -> f64 {
+    2.0
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            initial_delay_seconds: default_initial_delay_seconds(),
+            backoff_multiplier: default_backoff_multiplier(),
+            maximum_delay_seconds: Some(60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before the given retry (the first retry is attempt 2)
+    pub fn delay_before(&self, attempt: u32) -> std::time::Duration {
+        let exponent = attempt.saturating_sub(2) as i32;
+        let mut seconds = self.initial_delay_seconds as f64 * self.backoff_multiplier.powi(exponent);
+        if let Some(maximum) = self.maximum_delay_seconds {
+            seconds = seconds.min(maximum as f64);
+        }
+        std::time::Duration::from_secs_f64(seconds.max(0.0))
+    }
+}
+
+/// A `${...}` reference inside a step input, condition or output
+#[derive(Debug, Clone, PartialEq)]
+pub enum Reference {
+    Param(String),
+    StepOutput { step_id: String, path: Vec<String> },
+    Context(String),
+}
+
+const CONTEXT_FIELDS: [&str; 3] = ["tenant_id", "user_id", "workflow_id"];
+
+impl Reference {
+    fn parse(expression: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = expression.trim().split('.').collect();
+        match parts.as_slice() {
+            ["params", name] if !name.is_empty() => Ok(Self::Param(name.to_string())),
+            ["context", field] if CONTEXT_FIELDS.contains(field) => Ok(Self::Context(field.to_string())),
+            ["steps", step_id, "output", path @ ..] if !step_id.is_empty() => Ok(Self::StepOutput {
+                step_id: step_id.to_string(),
+                path: path.iter().map(|segment| segment.to_string()).collect(),
+            }),
+            _ => Err(format!("Invalid reference '${{{}}}'", expression)),
+        }
+    }
+}
+
+/// Split a string into literal text and references
+fn tokenize(text: &str) -> Result<Vec<Token>, String> {
+    let mut tokens = Vec::new();
+    let mut rest = text;
+
+    while let Some(start) = rest.find("${") {
+        if start > 0 {
+            tokens.push(Token::Text(rest[..start].to_string()));
+        }
+        let after = &rest[start + 2..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| format!("Unterminated reference in '{}'", text))?;
+        tokens.push(Token::Reference(Reference::parse(&after[..end])?));
+        rest = &after[end + 1..];
+    }
+    if !rest.is_empty() {
+        tokens.push(Token::Text(rest.to_string()));
+    }
+
+    Ok(tokens)
+}
+
+enum Token {
+    Text(String),
+    Reference(Reference),
+}
+
+/// Every reference in a value, in document order
+pub fn references(value: &Value) -> Result<Vec<Reference>, String> {
+    let mut found = Vec::new();
+    collect_references(value, &mut found)?;
+    Ok(found)
+}
+
+fn collect_references(value: &Value, found: &mut Vec<Reference>) -> Result<(), String> {
+    match value {
+        Value::String(text) => {
+            for token in tokenize(text)? {
+                if let Token::Reference(reference) = token {
+                    found.push(reference);
+                }
+            }
+        }
+        Value::Array(items) => {
+            for item in items {
+                collect_references(item, found)?;
+            }
+        }
+        Value::Object(fields) => {
+            for item in fields.values() {
+                collect_references(item, found)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+/// Values references resolve against while a template runs
+pub struct Scope<'a> {
+    pub params: &'a Map<String, Value>,
+    pub outputs: &'a HashMap<String, Value>,
+    pub context: &'a Map<String, Value>,
+}
+
+impl Scope<'_> {
+    fn lookup(&self, reference: &Reference) -> Result<Value, String> {
+        match reference {
+            Reference::Param(name) => Ok(self.params.get(name).cloned().unwrap_or(Value::Null)),
+            Reference::Context(field) => Ok(self.context.get(field).cloned().unwrap_or(Value::Null)),
+            Reference::StepOutput { step_id, path } => {
+                let mut value = self
+                    .outputs
+                    .get(step_id)
+                    .ok_or_else(|| format!("Step '{}' has no output", step_id))?;
+                for segment in path {
+                    value = match value {
+                        Value::Object(fields) => fields.get(segment),
+                        Value::Array(items) => segment.parse::<usize>().ok().and_then(|index| items.get(index)),
+                        _ => None,
+                    }
+                    .ok_or_else(|| format!("Output of step '{}' has no field '{}'", step_id, path.join(".")))?;
+                }
+                Ok(value.clone())
+            }
+        }
+    }
+
+    /// Replace every reference in `value`
+    pub fn resolve(&self, value: &Value) -> Result<Value, String> {
+        match value {
+            Value::String(text) => {
+                let tokens = tokenize(text)?;
+                if let [Token::Reference(reference)] = tokens.as_slice() {
+                    return self.lookup(reference);
+                }
+
+                let mut resolved = String::new();
+                for token in tokens {
+                    match token {
+                        Token::Text(text) => resolved.push_str(&text),
+                        Token::Reference(reference) => match self.lookup(&reference)? {
+                            Value::String(text) => resolved.push_str(&text),
+                            Value::Null => {}
+                            other => resolved.push_str(&other.to_string()),
+                        },
+                    }
+                }
+                Ok(Value::String(resolved))
+            }
+            Value::Array(items) => items.iter().map(|item| self.resolve(item)).collect::<Result<_, _>>().map(Value::Array),
+            Value::Object(fields) => fields
+                .iter()
+                .map(|(key, item)| Ok((key.clone(), self.resolve(item)?)))
+                .collect::<Result<Map<_, _>, String>>()
+                .map(Value::Object),
+            other => Ok(other.clone()),
+        }
+    }
+}
+
+/// Truthiness used by `when` conditions
+pub fn is_truthy(value: &Value) -> bool {
+    match value {
+        Value::Null => false,
+        Value::Bool(flag) => *flag,
+        Value::Number(number) => number.as_f64().map(|n| n != 0.0).unwrap_or(true),
+        Value::String(text) => !text.is_empty(),
+        Value::Array(items) => !items.is_empty(),
+        Value::Object(fields) => !fields.is_empty(),
+    }
+}
+
+impl TemplateDefinition {
+    pub fn step(&self, step_id: &str) -> Option<&TemplateStep> {
+        self.steps.iter().find(|step| step.step_id == step_id)
+    }
+
+    fn is_compensation(&self, step_id: &str) -> bool {
+        self.error_handling.compensation_steps.iter().any(|id| id == step_id)
+    }
+
+    /// Steps of the normal run in dependency order, ties broken by position
+    /// in the document. Fails if the dependencies form a cycle.
+    pub fn execution_order(&self) -> Result<Vec<&TemplateStep>, String> {
+        let main: Vec<&TemplateStep> = self.steps.iter().filter(|step| !self.is_compensation(&step.step_id)).collect();
+        let mut remaining: HashMap<&str, usize> = main
+            .iter()
+            .map(|step| (step.step_id.as_str(), step.depends_on.len()))
+            .collect();
+
+        let mut ready: VecDeque<&TemplateStep> = main.iter().copied().filter(|step| step.depends_on.is_empty()).collect();
+        let mut order = Vec::with_capacity(main.len());
+
+        while let Some(step) = ready.pop_front() {
+            order.push(step);
+            for dependent in &main {
+                if dependent.depends_on.contains(&step.step_id) {
+                    let count = remaining.get_mut(dependent.step_id.as_str()).expect("step is counted");
+                    *count -= 1;
+                    if *count == 0 {
+                        ready.push_back(dependent);
+                    }
+                }
+            }
+        }
+
+        if order.len() != main.len() {
+            let stuck: Vec<&str> = main
+                .iter()
+                .filter(|step| remaining[step.step_id.as_str()] > 0)
+                .map(|step| step.step_id.as_str())
+                .collect();
+            return Err(format!("Steps {} form a dependency cycle", stuck.join(", ")));
+        }
+        Ok(order)
+    }
+
+    /// Steps a step transitively depends on
+    fn ancestors(&self, step: &TemplateStep) -> HashSet<String> {
+        let mut seen = HashSet::new();
+        let mut pending: Vec<&String> = step.depends_on.iter().collect();
+        while let Some(step_id) = pending.pop() {
+            if seen.insert(step_id.clone()) {
+                if let Some(dependency) = self.step(step_id) {
+                    pending.extend(dependency.depends_on.iter());
+                }
+            }
+        }
+        seen
+    }
+
+    /// Check the definition without running it. Errors make the template
+    /// unusable; warnings point at likely mistakes.
+    pub fn validate(&self) -> ValidationResults {
+        let mut errors = Vec::new();
+        let mut warnings = Vec::new();
+
+        self.validate_parameters(&mut errors);
+        self.validate_steps(&mut errors, &mut warnings);
+
+        if errors.is_empty() {
+            if let Err(error) = self.execution_order() {
+                errors.push(error);
+            } else {
+                self.validate_references(&mut errors);
+            }
+        }
+
+        ValidationResults {
+            valid: errors.is_empty(),
+            warnings,
+            errors,
+        }
+    }
+
+    fn validate_parameters(&self, errors: &mut Vec<String>) {
+        let mut names = HashSet::new();
+        for parameter in &self.parameters {
+            if parameter.name.is_empty() || !is_identifier(&parameter.name) {
+                errors.push(format!("Parameter name '{}' must be letters, digits and underscores", parameter.name));
+            }
+            if !names.insert(parameter.name.as_str()) {
+                errors.push(format!("Parameter '{}' is declared more than once", parameter.name));
+            }
+            for rule in &parameter.validation_rules {
+                if let Err(error) = Rule::parse(rule) {
+                    errors.push(format!("Parameter '{}': {}", parameter.name, error));
+                }
+            }
+            if let Some(default) = &parameter.default_value {
+                if let Err(error) = check_value(parameter, default) {
+                    errors.push(format!("Default of parameter '{}' is invalid: {}", parameter.name, error));
+                }
+            }
+        }
+    }
+
+    fn validate_steps(&self, errors: &mut Vec<String>, warnings: &mut Vec<String>) {
+        if self.steps.is_empty() {
+            errors.push("Template must have at least one step".to_string());
+        }
+        if self.steps.len() > MAX_STEPS {
+            errors.push(format!("Template has {} steps; at most {} are allowed", self.steps.len(), MAX_STEPS));
+        }
+
+        let mut ids = HashSet::new();
+        for step in &self.steps {
+            if !is_identifier(&step.step_id) {
+                errors.push(format!("Step id '{}' must be letters, digits and underscores", step.step_id));
+            }
+            if !ids.insert(step.step_id.as_str()) {
+                errors.push(format!("Step id '{}' is used more than once", step.step_id));
+            }
+
+            match step.step_type {
+                StepType::Activity => match step.activity_type.as_deref() {
+                    Some(activity) if ACTIVITY_TYPES.contains(&activity) => {}
+                    Some(activity) => errors.push(format!("Step '{}' uses unknown activity '{}'", step.step_id, activity)),
+                    None => errors.push(format!("Activity step '{}' needs an activity_type", step.step_id)),
+                },
+                StepType::SubWorkflow => match step.workflow_type.as_deref() {
+                    Some(workflow) if SUB_WORKFLOW_TYPES.contains(&workflow) => {}
+                    Some(workflow) => errors.push(format!("Step '{}' uses unknown workflow '{}'", step.step_id, workflow)),
+                    None => errors.push(format!("Sub-workflow step '{}' needs a workflow_type", step.step_id)),
+                },
+                StepType::Approval => {
+                    match &step.approval {
+                        Some(policy) => policy.validate(&format!("Step '{}'", step.step_id), errors),
+                        None => errors.push(format!("Approval step '{}' needs an approval block", step.step_id)),
+                    }
+                    if step.timeout_seconds.is_some() || step.retry_policy.is_some() {
+                        errors.push(format!(
+                            "Approval step '{}' is never retried; set approval.timeout_seconds instead",
+                            step.step_id
+                        ));
+                    }
+                    if self.is_compensation(&step.step_id) {
+                        errors.push(format!("Approval step '{}' cannot be a compensation step", step.step_id));
+                    }
+                }
+            }
+            if step.approval.is_some() && step.step_type != StepType::Approval {
+                errors.push(format!("Only approval steps take an approval block; '{}' is not one", step.step_id));
+            }
+
+            if let Some(timeout) = step.timeout_seconds {
+                if timeout == 0 || timeout > MAX_STEP_TIMEOUT_SECONDS {
+                    errors.push(format!(
+                        "Step '{}' timeout must be between 1 and {} seconds",
+                        step.step_id, MAX_STEP_TIMEOUT_SECONDS
+                    ));
+                }
+            }
+            if let Some(policy) = &step.retry_policy {
+                validate_retry_policy(policy, &format!("Step '{}'", step.step_id), errors);
+            }
+
+            for dependency in &step.depends_on {
+                if self.step(dependency).is_none() {
+                    errors.push(format!("Step '{}' depends on non-existent step '{}'", step.step_id, dependency));
+                } else if self.is_compensation(dependency) {
+                    errors.push(format!("Step '{}' depends on compensation step '{}'", step.step_id, dependency));
+                }
+            }
+
+            if let Some(condition) = &step.when {
+                match tokenize(condition) {
+                    Ok(tokens) if matches!(tokens.as_slice(), [Token::Reference(_)]) => {}
+                    Ok(_) => errors.push(format!("Step '{}' condition must be a single reference", step.step_id)),
+                    Err(error) => errors.push(format!("Step '{}' condition: {}", step.step_id, error)),
+                }
+            }
+        }
+
+        validate_retry_policy(&self.error_handling.default_retry_policy, "Default retry policy", errors);
+
+        for step_id in &self.error_handling.compensation_steps {
+            match self.step(step_id) {
+                None => errors.push(format!("Compensation step '{}' does not exist", step_id)),
+                Some(step) if !step.depends_on.is_empty() => {
+                    errors.push(format!("Compensation step '{}' cannot have dependencies", step_id))
+                }
+                Some(_) => {}
+            }
+        }
+
+        let mut output_names = HashSet::new();
+        for output in &self.outputs {
+            if !output_names.insert(output.name.as_str()) {
+                errors.push(format!("Output '{}' is declared more than once", output.name));
+            }
+        }
+
+        for step in &self.steps {
+            if step.continue_on_error && self.is_compensation(&step.step_id) {
+                warnings.push(format!(
+                    "continue_on_error has no effect on compensation step '{}'",
+                    step.step_id
+                ));
+            }
+        }
+        if self.outputs.is_empty() {
+            warnings.push("Template declares no outputs".to_string());
+        }
+    }
+
+    /// References may only name declared parameters and steps the
+    /// referencing step is guaranteed to run after
+    fn validate_references(&self, errors: &mut Vec<String>) {
+        let parameters: HashSet<&str> = self.parameters.iter().map(|parameter| parameter.name.as_str()).collect();
+        let main_steps: HashSet<String> = self
+            .steps
+            .iter()
+            .filter(|step| !self.is_compensation(&step.step_id))
+            .map(|step| step.step_id.clone())
+            .collect();
+
+        let mut check = |owner: &str, value: &Value, visible_steps: &HashSet<String>| match references(value) {
+            Err(error) => errors.push(format!("{}: {}", owner, error)),
+            Ok(found) => {
+                for reference in found {
+                    match reference {
+                        Reference::Param(name) if !parameters.contains(name.as_str()) => {
+                            errors.push(format!("{} references undeclared parameter '{}'", owner, name))
+                        }
+                        Reference::StepOutput { step_id, .. } if !visible_steps.contains(&step_id) => errors.push(format!(
+                            "{} references step '{}', which it does not depend on",
+                            owner, step_id
+                        )),
+                        _ => {}
+                    }
+                }
+            }
+        };
+
+        for step in &self.steps {
+            let owner = format!("Step '{}'", step.step_id);
+            // Compensation steps run after a failure and may read any output
+            // produced before it
+            let visible = if self.is_compensation(&step.step_id) {
+                main_steps.clone()
+            } else {
+                self.ancestors(step)
+            };
+
+            check(&owner, &Value::Object(step.parameters.clone()), &visible);
+            // Assignees may name parameters or earlier outputs
+            if let Some(policy) = step.approval.as_ref().and_then(|policy| serde_json::to_value(policy).ok()) {
+                check(&owner, &policy, &visible);
+            }
+            if let Some(condition) = &step.when {
+                check(&owner, &Value::String(condition.clone()), &visible);
+            }
+        }
+
+        for output in &self.outputs {
+            check(&format!("Output '{}'", output.name), &Value::String(output.from.clone()), &main_steps);
+        }
+    }
+
+    /// Check supplied parameters and fill in defaults. Unknown parameters
+    /// are rejected so that typos do not silently fall back to defaults.
+    pub fn bind_parameters(&self, supplied: &Map<String, Value>) -> WorkflowServiceResult<Map<String, Value>> {
+        bind_parameters("Template", &self.parameters, supplied)
+    }
+
+    /// JSON schema of the parameters, stored alongside the template for
+    /// clients that build input forms
+    pub fn input_schema(&self) -> Value {
+        parameters_schema(&self.parameters)
+    }
+}
+
+/// Check a payload against declared parameters and fill in defaults.
+/// `owner` names what the parameters belong to in error messages.
+pub fn bind_parameters(
+    owner: &str,
+    parameters: &[TemplateParameter],
+    supplied: &Map<String, Value>,
+) -> WorkflowServiceResult<Map<String, Value>> {
+    if let Some(unknown) = supplied
+        .keys()
+        .find(|name| !parameters.iter().any(|parameter| &parameter.name == *name))
+    {
+        return Err(WorkflowServiceError::InvalidParameter(format!(
+            "{} has no parameter '{}'",
+            owner, unknown
+        )));
+    }
+
+    let mut bound = Map::new();
+    for parameter in parameters {
+        let value = match supplied.get(&parameter.name) {
+            Some(value) => value.clone(),
+            None => match &parameter.default_value {
+                Some(default) => default.clone(),
+                None if parameter.required => {
+                    return Err(WorkflowServiceError::MissingParameter(format!(
+                        "Required parameter '{}' is missing",
+                        parameter.name
+                    )))
+                }
+                None => continue,
+            },
+        };
+
+        check_value(parameter, &value).map_err(|error| {
+            WorkflowServiceError::InvalidParameter(format!("Parameter '{}' {}", parameter.name, error))
+        })?;
+        bound.insert(parameter.name.clone(), value);
+    }
+
+    Ok(bound)
+}
+
+/// JSON schema describing a parameter list
+pub fn parameters_schema(parameters: &[TemplateParameter]) -> Value {
+    let mut properties = Map::new();
+    let mut required = Vec::new();
+
+    for parameter in parameters {
+        let mut schema = Map::new();
+        if let Some(schema_type) = parameter.parameter_type.schema_type() {
+            schema.insert("type".to_string(), json!(schema_type));
+        }
+        if !parameter.description.is_empty() {
+            schema.insert("description".to_string(), json!(parameter.description));
+        }
+        if let Some(default) = &parameter.default_value {
+            schema.insert("default".to_string(), default.clone());
+        }
+        for rule in parameter.validation_rules.iter().filter_map(|rule| Rule::parse(rule).ok()) {
+            rule.describe(&mut schema);
+        }
+
+        if parameter.required && parameter.default_value.is_none() {
+            required.push(json!(parameter.name));
+        }
+        properties.insert(parameter.name.clone(), Value::Object(schema));
+    }
+
+    json!({
+        "type": "object",
+        "properties": properties,
+        "required": required,
+        "additionalProperties": false,
+    })
+}
+
+fn validate_retry_policy(policy: &RetryPolicy, owner: &str, errors: &mut Vec<String>) {
+    if policy.max_attempts == 0 || policy.max_attempts > MAX_RETRY_ATTEMPTS {
+        errors.push(format!("{}: max_attempts must be between 1 and {}", owner, MAX_RETRY_ATTEMPTS));
+    }
+    if !(1.0..=10.0).contains(&policy.backoff_multiplier) {
+        errors.push(format!("{}: backoff_multiplier must be between 1 and 10", owner));
+    }
+}
+
+fn is_identifier(text: &str) -> bool {
+    !text.is_empty() && text.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+impl ParameterType {
+    fn matches(self, value: &Value) -> bool {
+        match self {
+            ParameterType::String => value.is_string(),
+            ParameterType::Number => value.is_number(),
+            ParameterType::Integer => value.is_i64() || value.is_u64(),
+            ParameterType::Boolean => value.is_boolean(),
+            ParameterType::Array => value.is_array(),
+            ParameterType::Object => value.is_object(),
+            ParameterType::Any => true,
+        }
+    }
+
+    fn schema_type(self) -> Option<&'static str> {
+        match self {
+            ParameterType::String => Some("string"),
+            ParameterType::Number => Some("number"),
+            ParameterType::Integer => Some("integer"),
+            ParameterType::Boolean => Some("boolean"),
+            ParameterType::Array => Some("array"),
+            ParameterType::Object => Some("object"),
+            ParameterType::Any => None,
+        }
+    }
+}
+
+fn check_value(parameter: &TemplateParameter, value: &Value) -> Result<(), String> {
+    if !parameter.parameter_type.matches(value) {
+        return Err(format!("must be of type {:?}", parameter.parameter_type).to_lowercase());
+    }
+    for rule in &parameter.validation_rules {
+        Rule::parse(rule)?.check(value)?;
+    }
+    Ok(())
+}
+
+enum Rule {
+    EmailFormat,
+    UuidFormat,
+    NonEmpty,
+    MinLength(usize),
+    MaxLength(usize),
+    Min(f64),
+    Max(f64),
+    OneOf(Vec<String>),
+}
+
+impl Rule {
+    fn parse(rule: &str) -> Result<Self, String> {
+        let (name, argument) = match rule.split_once(':') {
+            Some((name, argument)) => (name.trim(), Some(argument.trim())),
+            None => (rule.trim(), None),
+        };
+
+        let number = |argument: Option<&str>| {
+            argument
+                .and_then(|argument| argument.parse::<f64>().ok())
+                .ok_or_else(|| format!("Rule '{}' needs a numeric argument", rule))
+        };
+        let length = |argument: Option<&str>| {
+            argument
+                .and_then(|argument| argument.parse::<usize>().ok())
+                .ok_or_else(|| format!("Rule '{}' needs a length argument", rule))
+        };
+
+        match name {
+            "email_format" => Ok(Self::EmailFormat),
+            "uuid_format" => Ok(Self::UuidFormat),
+            "non_empty" => Ok(Self::NonEmpty),
+            "min_length" => Ok(Self::MinLength(length(argument)?)),
+            "max_length" => Ok(Self::MaxLength(length(argument)?)),
+            "min" => Ok(Self::Min(number(argument)?)),
+            "max" => Ok(Self::Max(number(argument)?)),
+            "one_of" => match argument {
+                Some(options) if !options.is_empty() => {
+                    Ok(Self::OneOf(options.split('|').map(|option| option.trim().to_string()).collect()))
+                }
+                _ => Err(format!("Rule '{}' needs a list of options", rule)),
+            },
+            _ => Err(format!("Unknown validation rule '{}'", rule)),
+        }
+    }
+
+    fn check(&self, value: &Value) -> Result<(), String> {
+        let length = match value {
+            Value::String(text) => Some(text.chars().count()),
+            Value::Array(items) => Some(items.len()),
+            Value::Object(fields) => Some(fields.len()),
+            _ => None,
+        };
+
+        match self {
+            Self::EmailFormat => match value.as_str() {
+                Some(text) if looks_like_email(text) => Ok(()),
+                _ => Err("must be an email address".to_string()),
+            },
+            Self::UuidFormat => match value.as_str().map(uuid::Uuid::parse_str) {
+                Some(Ok(_)) => Ok(()),
+                _ => Err("must be a UUID".to_string()),
+            },
+            Self::NonEmpty => match length {
+                Some(0) => Err("must not be empty".to_string()),
+                _ => Ok(()),
+            },
+            Self::MinLength(min) => match length {
+                Some(length) if length < *min => Err(format!("must have at least {} characters or items", min)),
+                _ => Ok(()),
+            },
+            Self::MaxLength(max) => match length {
+                Some(length) if length > *max => Err(format!("must have at most {} characters or items", max)),
+                _ => Ok(()),
+            },
+            Self::Min(min) => match value.as_f64() {
+                Some(number) if number < *min => Err(format!("must be at least {}", min)),
+                _ => Ok(()),
+            },
+            Self::Max(max) => match value.as_f64() {
+                Some(number) if number > *max => Err(format!("must be at most {}", max)),
+                _ => Ok(()),
+            },
+            Self::OneOf(options) => {
+                let text = match value {
+                    Value::String(text) => text.clone(),
+                    other => other.to_string(),
+                };
+                if options.contains(&text) {
+                    Ok(())
+                } else {
+                    Err(format!("must be one of {}", options.join(", ")))
+                }
+            }
+        }
+    }
+
+    fn describe(&self, schema: &mut Map<String, Value>) {
+        let (key, value) = match self {
+            Self::EmailFormat => ("format", json!("email")),
+            Self::UuidFormat => ("format", json!("uuid")),
+            Self::NonEmpty => ("minLength", json!(1)),
+            Self::MinLength(min) => ("minLength", json!(min)),
+            Self::MaxLength(max) => ("maxLength", json!(max)),
+            Self::Min(min) => ("minimum", json!(min)),
+            Self::Max(max) => ("maximum", json!(max)),
+            Self::OneOf(options) => ("enum", json!(options)),
+        };
+        schema.insert(key.to_string(), value);
+    }
+}
+
+fn looks_like_email(text: &str) -> bool {
+    match text.split_once('@') {
+        Some((local, domain)) => {
+            !local.is_empty()
+                && domain.contains('.')
+                && !domain.starts_with('.')
+                && !domain.ends_with('.')
+                && !text.chars().any(char::is_whitespace)
+        }
+        None => false,
+    }
+}
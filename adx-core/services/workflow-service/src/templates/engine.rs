@@ -0,0 +1,450 @@
+use chrono::{DateTime, Utc};
+use serde::{de::DeserializeOwned, Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::{collections::HashMap, future::Future, time::Duration};
+use tracing::{info, warn};
+
+use super::dsl::{is_truthy, RetryPolicy, Scope, StepType, TemplateDefinition, TemplateStep};
+use crate::{
+    activities::CrossServiceActivities,
+    approvals::{approval_step, ApprovalActivities, ApprovalPolicy, CreateApprovalTaskRequest},
+    error::{WorkflowServiceError, WorkflowServiceResult},
+    workflows::*,
+};
+
+/// Workflow type the worker runs template instances under
+pub const TEMPLATE_WORKFLOW_TYPE: &str = "template_workflow";
+
+/// Activities a template step may call, named after the
+/// `CrossServiceActivities` methods. `coordinate_service_health_check`
+/// takes `{"services": [...]}`; every other activity takes its request type.
+pub const ACTIVITY_TYPES: [&str; 20] = [
+    "create_user_account",
+    "validate_user_credentials",
+    "update_user_session",
+    "revoke_user_sessions",
+    "create_user_profile",
+    "update_user_tenant_context",
+    "get_user_data_for_export",
+    "delete_user_data",
+    "validate_tenant_access",
+    "get_tenant_context",
+    "update_tenant_user_membership",
+    "get_tenant_data_for_migration",
+    "setup_user_file_workspace",
+    "migrate_user_files",
+    "export_user_files",
+    "delete_user_files",
+    "coordinate_service_health_check",
+    "create_cross_service_backup",
+    "restore_from_backup",
+    "send_notification",
+];
+
+/// Built-in workflows a template step may run as a child workflow
+pub const SUB_WORKFLOW_TYPES: [&str; 5] = [
+    "user_onboarding_workflow",
+    "tenant_switching_workflow",
+    "data_migration_workflow",
+    "bulk_operation_workflow",
+    "compliance_workflow",
+];
+
+/// Input of a template instance. The definition is copied in at start so
+/// that later edits to the template do not change running instances.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateWorkflowInput {
+    pub workflow_id: String,
+    pub template_id: String,
+    pub template_version: u32,
+    pub tenant_id: String,
+    pub user_id: Option<String>,
+    pub definition: TemplateDefinition,
+    /// Parameters after validation, with defaults filled in
+    pub parameters: Map<String, Value>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TemplateWorkflowResult {
+    pub workflow_id: String,
+    pub template_id: String,
+    pub outputs: Map<String, Value>,
+    pub steps: Vec<StepExecution>,
+    pub completed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum StepStatus {
+    Completed,
+    Skipped,
+    Failed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StepExecution {
+    pub step_id: String,
+    pub status: StepStatus,
+    pub attempts: u32,
+    pub output: Option<Value>,
+    pub error: Option<String>,
+    pub started_at: DateTime<Utc>,
+    pub completed_at: DateTime<Utc>,
+}
+
+/// Run a template instance: steps in dependency order with their retry
+/// policies and timeouts, then the declared outputs. When a step fails
+/// without `continue_on_error`, the compensation steps run and the
+/// workflow fails.
+pub async fn template_workflow(
+    input: TemplateWorkflowInput,
+    activities: &dyn CrossServiceActivities,
+    approvals: &dyn ApprovalActivities,
+) -> WorkflowServiceResult<TemplateWorkflowResult> {
+    info!(
+        "Starting template workflow {} from template {} v{}",
+        input.workflow_id, input.template_id, input.template_version
+    );
+
+    let definition = &input.definition;
+    let order = definition
+        .execution_order()
+        .map_err(WorkflowServiceError::InvalidTemplate)?;
+
+    let mut context = Map::new();
+    context.insert("tenant_id".to_string(), json!(input.tenant_id));
+    context.insert("user_id".to_string(), json!(input.user_id));
+    context.insert("workflow_id".to_string(), json!(input.workflow_id));
+
+    let mut outputs: HashMap<String, Value> = HashMap::new();
+    let mut executions: Vec<StepExecution> = Vec::new();
+
+    for step in order {
+        let started_at = Utc::now();
+
+        // A step only runs when everything it depends on completed
+        if let Some(dependency) = step.depends_on.iter().find(|id| !outputs.contains_key(*id)) {
+            executions.push(StepExecution {
+                step_id: step.step_id.clone(),
+                status: StepStatus::Skipped,
+                attempts: 0,
+                output: None,
+                error: Some(format!("Dependency '{}' did not complete", dependency)),
+                started_at,
+                completed_at: Utc::now(),
+            });
+            continue;
+        }
+
+        let scope = Scope {
+            params: &input.parameters,
+            outputs: &outputs,
+            context: &context,
+        };
+
+        if let Some(condition) = &step.when {
+            let value = scope
+                .resolve(&Value::String(condition.clone()))
+                .map_err(WorkflowServiceError::InvalidTemplate)?;
+            if !is_truthy(&value) {
+                info!("Skipping step {}: condition {} is not met", step.step_id, condition);
+                executions.push(StepExecution {
+                    step_id: step.step_id.clone(),
+                    status: StepStatus::Skipped,
+                    attempts: 0,
+                    output: None,
+                    error: None,
+                    started_at,
+                    completed_at: Utc::now(),
+                });
+                continue;
+            }
+        }
+
+        let (result, attempts) = run_step(step, definition, &scope, &input, activities, approvals).await;
+        match result {
+            Ok(output) => {
+                outputs.insert(step.step_id.clone(), output.clone());
+                executions.push(StepExecution {
+                    step_id: step.step_id.clone(),
+                    status: StepStatus::Completed,
+                    attempts,
+                    output: Some(output),
+                    error: None,
+                    started_at,
+                    completed_at: Utc::now(),
+                });
+            }
+            Err(error) => {
+                executions.push(StepExecution {
+                    step_id: step.step_id.clone(),
+                    status: StepStatus::Failed,
+                    attempts,
+                    output: None,
+                    error: Some(error.to_string()),
+                    started_at,
+                    completed_at: Utc::now(),
+                });
+
+                if step.continue_on_error {
+                    warn!("Step {} failed, continuing: {}", step.step_id, error);
+                    continue;
+                }
+
+                let compensated = compensate(definition, &input, &outputs, &context, activities, approvals).await;
+                return Err(WorkflowServiceError::WorkflowExecution(format!(
+                    "Template workflow {} failed at step '{}' after {} attempt(s): {}; {} compensation step(s) completed",
+                    input.workflow_id, step.step_id, attempts, error, compensated
+                )));
+            }
+        }
+    }
+
+    let scope = Scope {
+        params: &input.parameters,
+        outputs: &outputs,
+        context: &context,
+    };
+    let mut result_outputs = Map::new();
+    for output in &definition.outputs {
+        // Outputs of skipped steps are reported as null
+        let value = scope.resolve(&Value::String(output.from.clone())).unwrap_or(Value::Null);
+        result_outputs.insert(output.name.clone(), value);
+    }
+
+    info!("Template workflow {} completed", input.workflow_id);
+    Ok(TemplateWorkflowResult {
+        workflow_id: input.workflow_id,
+        template_id: input.template_id,
+        outputs: result_outputs,
+        steps: executions,
+        completed_at: Utc::now(),
+    })
+}
+
+/// Run the compensation steps once each, in order, and report how many
+/// completed. Failures are logged; compensation carries on regardless.
+async fn compensate(
+    definition: &TemplateDefinition,
+    input: &TemplateWorkflowInput,
+    outputs: &HashMap<String, Value>,
+    context: &Map<String, Value>,
+    activities: &dyn CrossServiceActivities,
+    approvals: &dyn ApprovalActivities,
+) -> usize {
+    let scope = Scope {
+        params: &input.parameters,
+        outputs,
+        context,
+    };
+
+    let mut completed = 0;
+    for step_id in &definition.error_handling.compensation_steps {
+        let Some(step) = definition.step(step_id) else {
+            continue;
+        };
+        match run_step(step, definition, &scope, input, activities, approvals).await {
+            (Ok(_), _) => completed += 1,
+            (Err(error), _) => warn!("Compensation step {} of {} failed: {}", step_id, input.workflow_id, error),
+        }
+    }
+    completed
+}
+
+/// Resolve a step's input and run it under its retry policy. Returns the
+/// outcome and the number of attempts made.
+async fn run_step(
+    step: &TemplateStep,
+    definition: &TemplateDefinition,
+    scope: &Scope<'_>,
+    instance: &TemplateWorkflowInput,
+    activities: &dyn CrossServiceActivities,
+    approvals: &dyn ApprovalActivities,
+) -> (WorkflowServiceResult<Value>, u32) {
+    let input = match step_input(step, scope, &instance.tenant_id) {
+        Ok(input) => input,
+        Err(error) => return (Err(error), 0),
+    };
+
+    if step.step_type == StepType::Approval {
+        return (run_approval(step, scope, input, instance, approvals).await, 1);
+    }
+
+    let policy: &RetryPolicy = step
+        .retry_policy
+        .as_ref()
+        .unwrap_or(&definition.error_handling.default_retry_policy);
+    let mut attempt = 1;
+
+    loop {
+        let outcome = match step.timeout_seconds {
+            Some(seconds) => tokio::time::timeout(Duration::from_secs(seconds), dispatch(step, input.clone(), activities))
+                .await
+                .unwrap_or_else(|_| {
+                    Err(WorkflowServiceError::ActivityExecution(format!(
+                        "Step '{}' timed out after {}s",
+                        step.step_id, seconds
+                    )))
+                }),
+            None => dispatch(step, input.clone(), activities).await,
+        };
+
+        match outcome {
+            Ok(output) => return (Ok(output), attempt),
+            // Malformed input fails the same way every time
+            Err(error @ WorkflowServiceError::InvalidParameter(_)) => return (Err(error), attempt),
+            Err(error) if attempt >= policy.max_attempts => return (Err(error), attempt),
+            Err(error) => {
+                attempt += 1;
+                let delay = policy.delay_before(attempt);
+                warn!(
+                    "Step {} failed ({}), retrying in {:?} (attempt {}/{})",
+                    step.step_id, error, delay, attempt, policy.max_attempts
+                );
+                tokio::time::sleep(delay).await;
+            }
+        }
+    }
+}
+
+/// Approval steps wait on people, so they run once under the deadline in
+/// their policy rather than under a retry policy and step timeout
+async fn run_approval(
+    step: &TemplateStep,
+    scope: &Scope<'_>,
+    context: Value,
+    instance: &TemplateWorkflowInput,
+    approvals: &dyn ApprovalActivities,
+) -> WorkflowServiceResult<Value> {
+    let policy = step
+        .approval
+        .as_ref()
+        .ok_or_else(|| WorkflowServiceError::InvalidTemplate(format!("Approval step '{}' has no policy", step.step_id)))?;
+    let policy = scope
+        .resolve(&serde_json::to_value(policy)?)
+        .map_err(|e| WorkflowServiceError::InvalidParameter(format!("Step '{}': {}", step.step_id, e)))?;
+    let policy: ApprovalPolicy = request(step, policy)?;
+
+    output(approval_step(
+        approvals,
+        CreateApprovalTaskRequest {
+            tenant_id: instance.tenant_id.clone(),
+            workflow_id: instance.workflow_id.clone(),
+            step_id: step.step_id.clone(),
+            requested_by: instance.user_id.clone(),
+            policy,
+            context,
+        },
+    ))
+    .await
+}
+
+/// Fields naming the tenant a step reads or writes
+const TENANT_FIELDS: [&str; 3] = ["tenant_id", "source_tenant_id", "target_tenant_id"];
+
+/// A step's input with references resolved. Steps act on the instance's
+/// tenant: `tenant_id` defaults to it and no tenant field may name another
+/// tenant. Tenant switching is the exception for its target, which the
+/// workflow checks against the user's memberships itself.
+fn step_input(step: &TemplateStep, scope: &Scope<'_>, tenant_id: &str) -> WorkflowServiceResult<Value> {
+    let mut input = scope
+        .resolve(&Value::Object(step.parameters.clone()))
+        .map_err(|e| WorkflowServiceError::InvalidParameter(format!("Step '{}': {}", step.step_id, e)))?;
+
+    if let Value::Object(fields) = &mut input {
+        if matches!(fields.get("tenant_id"), None | Some(Value::Null)) {
+            fields.insert("tenant_id".to_string(), json!(tenant_id));
+        }
+
+        let switching = step.workflow_type.as_deref() == Some("tenant_switching_workflow");
+        for field in TENANT_FIELDS {
+            if switching && field == "target_tenant_id" {
+                continue;
+            }
+            match fields.get(field) {
+                None | Some(Value::Null) => {}
+                Some(Value::String(value)) if value == tenant_id => {}
+                Some(_) => {
+                    return Err(WorkflowServiceError::Authorization(format!(
+                        "Step '{}' may only act on tenant {}",
+                        step.step_id, tenant_id
+                    )))
+                }
+            }
+        }
+    }
+
+    Ok(input)
+}
+
+fn request<T: DeserializeOwned>(step: &TemplateStep, input: Value) -> WorkflowServiceResult<T> {
+    serde_json::from_value(input)
+        .map_err(|e| WorkflowServiceError::InvalidParameter(format!("Step '{}' input is invalid: {}", step.step_id, e)))
+}
+
+async fn output<T, F>(call: F) -> WorkflowServiceResult<Value>
+where
+    T: Serialize,
+    F: Future<Output = WorkflowServiceResult<T>>,
+{
+    Ok(serde_json::to_value(call.await?)?)
+}
+
+macro_rules! dispatch_activity {
+    ($step:expr, $name:expr, $input:expr, $activities:expr, [$($activity:ident),* $(,)?]) => {
+        match $name {
+            $(stringify!($activity) => output($activities.$activity(request($step, $input)?)).await,)*
+            other => Err(WorkflowServiceError::InvalidTemplate(format!("Unknown activity '{}'", other))),
+        }
+    };
+}
+
+async fn dispatch(step: &TemplateStep, input: Value, activities: &dyn CrossServiceActivities) -> WorkflowServiceResult<Value> {
+    match step.step_type {
+        StepType::Activity => {
+            let name = step.activity_type.as_deref().unwrap_or_default();
+            if name == "coordinate_service_health_check" {
+                #[derive(Deserialize)]
+                struct HealthCheckInput {
+                    services: Vec<String>,
+                }
+                let input: HealthCheckInput = request(step, input)?;
+                return output(activities.coordinate_service_health_check(input.services)).await;
+            }
+
+            dispatch_activity!(step, name, input, activities, [
+                create_user_account,
+                validate_user_credentials,
+                update_user_session,
+                revoke_user_sessions,
+                create_user_profile,
+                update_user_tenant_context,
+                get_user_data_for_export,
+                delete_user_data,
+                validate_tenant_access,
+                get_tenant_context,
+                update_tenant_user_membership,
+                get_tenant_data_for_migration,
+                setup_user_file_workspace,
+                migrate_user_files,
+                export_user_files,
+                delete_user_files,
+                create_cross_service_backup,
+                restore_from_backup,
+                send_notification,
+            ])
+        }
+        StepType::SubWorkflow => match step.workflow_type.as_deref().unwrap_or_default() {
+            "user_onboarding_workflow" => output(user_onboarding_workflow(request(step, input)?, activities)).await,
+            "tenant_switching_workflow" => output(tenant_switching_workflow(request(step, input)?, activities)).await,
+            "data_migration_workflow" => output(data_migration_workflow(request(step, input)?, activities)).await,
+            "bulk_operation_workflow" => output(bulk_operation_workflow(request(step, input)?, activities)).await,
+            "compliance_workflow" => output(compliance_workflow(request(step, input)?, activities)).await,
+            other => Err(WorkflowServiceError::InvalidTemplate(format!("Unknown workflow '{}'", other))),
+        },
+        StepType::Approval => Err(WorkflowServiceError::InvalidTemplate(format!(
+            "Approval step '{}' is not an activity",
+            step.step_id
+        ))),
+    }
+}
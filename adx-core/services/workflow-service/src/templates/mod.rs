@@ -0,0 +1,815 @@
+use crate::{
+    config::WorkflowServiceConfig,
+    error::{WorkflowServiceError, WorkflowServiceResult},
+    repositories::WorkflowTemplateRepository,
+    task_queues::TaskQueueRouter,
+};
+use adx_shared::temporal::{AdxTemporalClient, WorkflowSearchAttributes};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{Map, Value};
+use std::sync::Arc;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+pub mod dsl;
+pub mod engine;
+
+pub use dsl::*;
+pub use engine::*;
+
+/// Workflow template management: tenant templates written in the template
+/// DSL, stored in the database and instantiated as Temporal workflows
+pub struct WorkflowTemplateManager {
+    config: Arc<WorkflowServiceConfig>,
+    repository: Arc<dyn WorkflowTemplateRepository>,
+    temporal: AdxTemporalClient,
+    task_queues: Arc<TaskQueueRouter>,
+    pattern_analyzer: PatternAnalyzer,
+    template_generator: TemplateGenerator,
+}
+
+impl WorkflowTemplateManager {
+    pub fn new(
+        config: Arc<WorkflowServiceConfig>,
+        repository: Arc<dyn WorkflowTemplateRepository>,
+        temporal: AdxTemporalClient,
+        task_queues: Arc<TaskQueueRouter>,
+    ) -> Self {
+        Self {
+            config,
+            repository,
+            temporal,
+            task_queues,
+            pattern_analyzer: PatternAnalyzer::new(),
+            template_generator: TemplateGenerator::new(),
+        }
+    }
+
+    /// Create a new workflow template
+    pub async fn create_template(
+        &self,
+        tenant_id: &str,
+        user_id: Option<&str>,
+        document: TemplateDocument,
+    ) -> WorkflowServiceResult<CreateTemplateResponse> {
+        info!("Creating workflow template '{}' for tenant {}", document.name, tenant_id);
+
+        let created_by = user_id
+            .ok_or_else(|| WorkflowServiceError::Validation("X-User-ID header is required".to_string()))?
+            .to_string();
+        let validation_results = validate_document(&document)?;
+        let pattern_analysis = self.pattern_analyzer.analyze_template(&document.definition);
+
+        let now = Utc::now();
+        let template = WorkflowTemplate {
+            template_id: Uuid::new_v4().to_string(),
+            tenant_id: tenant_id.to_string(),
+            name: document.name.trim().to_string(),
+            description: document.description,
+            category: document.category,
+            tags: document.tags,
+            is_public: document.is_public,
+            version: 1,
+            task_queue: self.config.temporal.task_queue.clone(),
+            definition: document.definition,
+            created_by,
+            created_at: now,
+            updated_at: now,
+        };
+        self.repository.create_template(&template).await?;
+
+        Ok(CreateTemplateResponse {
+            template_id: template.template_id,
+            template_name: template.name,
+            version: template.version,
+            created: true,
+            created_at: now,
+            validation_results,
+            pattern_analysis,
+        })
+    }
+
+    /// Check a template document without storing it
+    pub fn validate_template(&self, document: &TemplateDocument) -> ValidateTemplateResponse {
+        let mut validation_results = document.definition.validate();
+        if document.name.trim().is_empty() {
+            validation_results.valid = false;
+            validation_results.errors.insert(0, "Template name is required".to_string());
+        }
+
+        ValidateTemplateResponse {
+            input_schema: document.definition.input_schema(),
+            validation_results,
+        }
+    }
+
+    /// Get the templates a tenant can use: its own and public ones
+    pub async fn get_templates(&self, tenant_id: &str, params: GetTemplatesParams) -> WorkflowServiceResult<GetTemplatesResponse> {
+        info!("Getting workflow templates with filters: {:?}", params);
+
+        let templates = self.repository.list_templates(tenant_id, &params).await?;
+
+        let mut categories: Vec<String> = templates
+            .iter()
+            .map(|template| template.category.clone())
+            .filter(|category| !category.is_empty())
+            .collect();
+        categories.sort();
+        categories.dedup();
+
+        let mut tags: Vec<String> = templates.iter().flat_map(|template| template.tags.clone()).collect();
+        tags.sort();
+        tags.dedup();
+
+        Ok(GetTemplatesResponse {
+            total_count: templates.len() as u32,
+            templates,
+            categories,
+            tags,
+        })
+    }
+
+    /// Get a specific template
+    pub async fn get_template(&self, tenant_id: &str, template_id: &str) -> WorkflowServiceResult<WorkflowTemplate> {
+        info!("Getting workflow template: {}", template_id);
+
+        self.repository
+            .get_template(tenant_id, template_id)
+            .await?
+            .ok_or_else(|| WorkflowServiceError::NotFound(format!("Template {}", template_id)))
+    }
+
+    /// Start a workflow from a template. Parameters are checked against the
+    /// template's declarations before anything is started.
+    pub async fn instantiate_template(
+        &self,
+        tenant_id: &str,
+        user_id: Option<&str>,
+        template_id: &str,
+        request: InstantiateTemplateRequest,
+    ) -> WorkflowServiceResult<InstantiateTemplateResponse> {
+        info!("Instantiating workflow template {} for tenant {}", template_id, tenant_id);
+
+        if user_id.is_some_and(|user_id| Uuid::parse_str(user_id).is_err()) {
+            return Err(WorkflowServiceError::Validation("X-User-ID must be a UUID".to_string()));
+        }
+        let template = self.get_template(tenant_id, template_id).await?;
+        let parameters = template.definition.bind_parameters(&request.parameters)?;
+
+        let workflow_name = request.workflow_name.unwrap_or_else(|| "template".to_string());
+        if workflow_name.is_empty() || workflow_name.len() > 100 || workflow_name.chars().any(char::is_whitespace) {
+            return Err(WorkflowServiceError::Validation(
+                "workflow_name must be 1-100 characters without whitespace".to_string(),
+            ));
+        }
+        let workflow_id = format!("{}_{}", workflow_name, Uuid::new_v4());
+        let task_queue = self.task_queues.route(tenant_id, TEMPLATE_WORKFLOW_TYPE).await?;
+
+        let input = TemplateWorkflowInput {
+            workflow_id: workflow_id.clone(),
+            template_id: template.template_id.clone(),
+            template_version: template.version,
+            tenant_id: tenant_id.to_string(),
+            user_id: user_id.map(str::to_string),
+            definition: template.definition,
+            parameters: parameters.clone(),
+        };
+
+        let handle = self
+            .temporal
+            .start_workflow_with_search_attributes::<TemplateWorkflowInput, TemplateWorkflowResult>(
+                TEMPLATE_WORKFLOW_TYPE,
+                workflow_id.clone(),
+                &task_queue,
+                input,
+                &WorkflowSearchAttributes::new(tenant_id, TEMPLATE_WORKFLOW_TYPE)
+                    .user(user_id)
+                    .business_key(format!("template:{}", template.template_id))
+                    .lane(self.task_queues.lane(TEMPLATE_WORKFLOW_TYPE, None)),
+            )
+            .await
+            .map_err(|e| WorkflowServiceError::Temporal(e.to_string()))?;
+        let run_id = handle.run_id().to_string();
+
+        let started_at = Utc::now();
+        let instance = TemplateInstance {
+            tenant_id: tenant_id.to_string(),
+            user_id: user_id.map(str::to_string),
+            template_id: template.template_id.clone(),
+            template_version: template.version,
+            workflow_id: workflow_id.clone(),
+            run_id: run_id.clone(),
+            task_queue,
+            parameters: Value::Object(parameters.clone()),
+            started_at,
+        };
+
+        // An untracked run would not show up in usage or block deletion
+        if let Err(e) = self.repository.record_instance(&instance).await {
+            if let Err(cancel) = self
+                .temporal
+                .cancel_workflow(&workflow_id, Some(&run_id), "Failed to record template instance")
+                .await
+            {
+                warn!("Failed to cancel workflow {} after a failed insert: {}", workflow_id, cancel);
+            }
+            return Err(e);
+        }
+
+        Ok(InstantiateTemplateResponse {
+            workflow_id,
+            run_id,
+            template_id: template.template_id,
+            template_version: template.version,
+            status: "running".to_string(),
+            parameters,
+            started_at,
+        })
+    }
+
+    /// Replace a template's document. Running instances keep the version
+    /// they were started with.
+    pub async fn update_template(
+        &self,
+        tenant_id: &str,
+        template_id: &str,
+        document: TemplateDocument,
+    ) -> WorkflowServiceResult<UpdateTemplateResponse> {
+        info!("Updating workflow template: {}", template_id);
+
+        let existing = self.get_template(tenant_id, template_id).await?;
+        if existing.tenant_id != tenant_id {
+            return Err(WorkflowServiceError::Authorization(
+                "Public templates can only be changed by the tenant that owns them".to_string(),
+            ));
+        }
+        validate_document(&document)?;
+
+        let updated = WorkflowTemplate {
+            name: document.name.trim().to_string(),
+            description: document.description,
+            category: document.category,
+            tags: document.tags,
+            is_public: document.is_public,
+            definition: document.definition,
+            version: existing.version + 1,
+            updated_at: Utc::now(),
+            ..existing.clone()
+        };
+        let changes_summary = summarize_changes(&existing, &updated);
+        self.repository.update_template(&updated).await?;
+
+        Ok(UpdateTemplateResponse {
+            template_id: updated.template_id,
+            updated: true,
+            updated_at: updated.updated_at,
+            new_version: updated.version,
+            changes_summary,
+        })
+    }
+
+    /// Delete a template
+    pub async fn delete_template(&self, tenant_id: &str, template_id: &str, force: bool) -> WorkflowServiceResult<DeleteTemplateResponse> {
+        info!("Deleting workflow template: {} (force: {})", template_id, force);
+
+        // Check for active workflows using this template
+        let active_workflows = self.repository.active_workflows(tenant_id, template_id).await?;
+
+        if !active_workflows.is_empty() && !force {
+            return Err(WorkflowServiceError::TemplateInUse(
+                format!("Template {} is used by {} active workflows", template_id, active_workflows.len())
+            ));
+        }
+
+        // Running instances carry their own copy of the definition and
+        // finish normally; their execution records lose the template link
+        if !self.repository.delete_template(tenant_id, template_id).await? {
+            return Err(WorkflowServiceError::NotFound(format!("Template {}", template_id)));
+        }
+
+        Ok(DeleteTemplateResponse {
+            template_id: template_id.to_string(),
+            deleted: true,
+            deleted_at: Utc::now(),
+            affected_workflows: active_workflows.len() as u32,
+            cleanup_performed: force,
+        })
+    }
+
+    /// Analyze workflow patterns to suggest templates
+    pub async fn analyze_workflow_patterns(&self, params: PatternAnalysisParams) -> WorkflowServiceResult<PatternAnalysisResponse> {
+        info!("Analyzing workflow patterns for tenant: {:?}", params.tenant_id);
+
+        let analysis = self.pattern_analyzer.analyze_workflow_patterns(&params).await?;
+
+        Ok(PatternAnalysisResponse {
+            analysis_id: Uuid::new_v4().to_string(),
+            patterns_found: analysis.patterns_found,
+            template_suggestions: analysis.template_suggestions,
+            optimization_opportunities: analysis.optimization_opportunities,
+            reusability_score: analysis.reusability_score,
+            analyzed_at: Utc::now(),
+        })
+    }
+
+    /// Draft a template from existing workflows. The draft is returned for
+    /// review rather than stored; submit it to the create endpoint once its
+    /// steps are filled in.
+    pub async fn generate_template_from_workflows(&self, request: GenerateTemplateRequest) -> WorkflowServiceResult<GenerateTemplateResponse> {
+        info!("Generating template from {} workflows", request.workflow_ids.len());
+
+        // Analyze common patterns across workflows
+        let pattern_analysis = self.pattern_analyzer.analyze_workflows(&request.workflow_ids).await?;
+
+        // Generate template definition
+        let definition = self.template_generator.generate_template_from_patterns(&pattern_analysis).await?;
+
+        let draft = TemplateDocument {
+            name: request.template_name,
+            description: request.description,
+            category: request.category,
+            tags: request.tags,
+            is_public: false,
+            definition,
+        };
+        let validation_results = draft.definition.validate();
+
+        Ok(GenerateTemplateResponse {
+            template_name: draft.name.clone(),
+            generated: true,
+            generated_at: Utc::now(),
+            source_workflows: request.workflow_ids,
+            confidence_score: pattern_analysis.confidence_score,
+            pattern_analysis,
+            draft,
+            validation_results,
+        })
+    }
+
+    /// Get template usage statistics
+    pub async fn get_template_usage(&self, tenant_id: &str, template_id: &str) -> WorkflowServiceResult<TemplateUsageResponse> {
+        info!("Getting usage statistics for template: {}", template_id);
+
+        // Confirms the template is visible to this tenant
+        self.get_template(tenant_id, template_id).await?;
+        let usage_stats = self.repository.template_usage(tenant_id, template_id).await?;
+
+        Ok(TemplateUsageResponse {
+            template_id: template_id.to_string(),
+            total_uses: usage_stats.total_uses,
+            active_workflows: usage_stats.active_workflows,
+            success_rate: usage_stats.success_rate,
+            average_duration_seconds: usage_stats.average_duration_seconds,
+            usage_trends: usage_stats.usage_trends,
+            last_used: usage_stats.last_used,
+        })
+    }
+}
+
+fn validate_document(document: &TemplateDocument) -> WorkflowServiceResult<ValidationResults> {
+    if document.name.trim().is_empty() {
+        return Err(WorkflowServiceError::InvalidTemplate("Template name is required".to_string()));
+    }
+
+    let results = document.definition.validate();
+    if !results.valid {
+        return Err(WorkflowServiceError::InvalidTemplate(results.errors.join("; ")));
+    }
+    Ok(results)
+}
+
+fn summarize_changes(before: &WorkflowTemplate, after: &WorkflowTemplate) -> Vec<String> {
+    let mut changes = Vec::new();
+
+    if before.name != after.name {
+        changes.push(format!("Renamed from '{}' to '{}'", before.name, after.name));
+    }
+    if before.description != after.description {
+        changes.push("Updated description".to_string());
+    }
+    if before.category != after.category || before.tags != after.tags {
+        changes.push("Updated category or tags".to_string());
+    }
+    if before.is_public != after.is_public {
+        changes.push(if after.is_public { "Made public" } else { "Made private" }.to_string());
+    }
+    if before.definition.parameters != after.definition.parameters {
+        changes.push("Changed parameters".to_string());
+    }
+
+    for step in &after.definition.steps {
+        match before.definition.step(&step.step_id) {
+            None => changes.push(format!("Added step '{}'", step.step_id)),
+            Some(previous) if previous != step => changes.push(format!("Changed step '{}'", step.step_id)),
+            Some(_) => {}
+        }
+    }
+    for step in &before.definition.steps {
+        if after.definition.step(&step.step_id).is_none() {
+            changes.push(format!("Removed step '{}'", step.step_id));
+        }
+    }
+
+    if before.definition.outputs != after.definition.outputs {
+        changes.push("Changed outputs".to_string());
+    }
+    if before.definition.error_handling != after.definition.error_handling {
+        changes.push("Changed error handling".to_string());
+    }
+    if changes.is_empty() {
+        changes.push("No changes".to_string());
+    }
+    changes
+}
+
+/// Pattern analysis service
+pub struct PatternAnalyzer {
+    // In a real implementation, this would analyze workflow patterns
+}
+
+impl PatternAnalyzer {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    /// Describe the shape of a template definition
+    pub fn analyze_template(&self, definition: &TemplateDefinition) -> PatternAnalysisResult {
+        let mut patterns_found = Vec::new();
+
+        let roots: Vec<String> = definition
+            .steps
+            .iter()
+            .filter(|step| step.depends_on.is_empty() && !definition.error_handling.compensation_steps.contains(&step.step_id))
+            .map(|step| step.step_id.clone())
+            .collect();
+        patterns_found.push(if roots.len() > 1 {
+            WorkflowPattern {
+                pattern_type: PatternType::Parallel,
+                description: "Independent steps without dependencies on each other".to_string(),
+                confidence: 1.0,
+                steps_involved: roots,
+            }
+        } else {
+            WorkflowPattern {
+                pattern_type: PatternType::Sequential,
+                description: "Sequential step execution".to_string(),
+                confidence: 1.0,
+                steps_involved: definition.steps.iter().map(|step| step.step_id.clone()).collect(),
+            }
+        });
+
+        let conditional: Vec<String> = definition
+            .steps
+            .iter()
+            .filter(|step| step.when.is_some())
+            .map(|step| step.step_id.clone())
+            .collect();
+        if !conditional.is_empty() {
+            patterns_found.push(WorkflowPattern {
+                pattern_type: PatternType::Conditional,
+                description: "Steps guarded by a condition".to_string(),
+                confidence: 1.0,
+                steps_involved: conditional,
+            });
+        }
+
+        let approvals: Vec<String> = definition
+            .steps
+            .iter()
+            .filter(|step| step.step_type == StepType::Approval)
+            .map(|step| step.step_id.clone())
+            .collect();
+        if !approvals.is_empty() {
+            patterns_found.push(WorkflowPattern {
+                pattern_type: PatternType::HumanApproval,
+                description: "Steps that wait for a human decision".to_string(),
+                confidence: 1.0,
+                steps_involved: approvals,
+            });
+        }
+
+        if !definition.error_handling.compensation_steps.is_empty() {
+            patterns_found.push(WorkflowPattern {
+                pattern_type: PatternType::Compensation,
+                description: "Compensation steps run when the workflow fails".to_string(),
+                confidence: 1.0,
+                steps_involved: definition.error_handling.compensation_steps.clone(),
+            });
+        }
+
+        PatternAnalysisResult {
+            patterns_found,
+            template_suggestions: vec![],
+            optimization_opportunities: vec![],
+            reusability_score: if definition.parameters.is_empty() { 0.5 } else { 0.85 },
+            confidence_score: 1.0,
+            extracted_parameters: definition.parameters.clone(),
+        }
+    }
+
+    pub async fn analyze_workflow_patterns(&self, params: &PatternAnalysisParams) -> WorkflowServiceResult<PatternAnalysisResult> {
+        Ok(PatternAnalysisResult {
+            patterns_found: vec![
+                WorkflowPattern {
+                    pattern_type: PatternType::Sequential,
+                    description: "Common sequential pattern".to_string(),
+                    confidence: 0.88,
+                    steps_involved: vec!["step1".to_string(), "step2".to_string()],
+                },
+            ],
+            template_suggestions: vec![
+                TemplateSuggestion {
+                    suggested_name: "Common User Flow".to_string(),
+                    description: "Template for common user operations".to_string(),
+                    confidence: 0.82,
+                    potential_savings: "30% development time".to_string(),
+                },
+            ],
+            optimization_opportunities: vec![
+                "Combine similar validation steps".to_string(),
+            ],
+            reusability_score: 0.78,
+            confidence_score: 0.85,
+            extracted_parameters: vec![],
+        })
+    }
+
+    pub async fn analyze_workflows(&self, workflow_ids: &[String]) -> WorkflowServiceResult<PatternAnalysisResult> {
+        Ok(PatternAnalysisResult {
+            patterns_found: vec![],
+            template_suggestions: vec![],
+            optimization_opportunities: vec![],
+            reusability_score: 0.75,
+            confidence_score: 0.80,
+            extracted_parameters: vec![
+                TemplateParameter {
+                    name: "user_id".to_string(),
+                    description: "User identifier".to_string(),
+                    parameter_type: ParameterType::String,
+                    required: true,
+                    default_value: None,
+                    validation_rules: vec!["uuid_format".to_string()],
+                },
+            ],
+        })
+    }
+}
+
+/// Template generation service
+pub struct TemplateGenerator {
+    // In a real implementation, this would generate templates
+}
+
+impl TemplateGenerator {
+    pub fn new() -> Self {
+        Self {}
+    }
+
+    pub async fn generate_template_from_patterns(&self, analysis: &PatternAnalysisResult) -> WorkflowServiceResult<TemplateDefinition> {
+        // Mock implementation
+        Ok(TemplateDefinition {
+            steps: vec![],
+            parameters: analysis.extracted_parameters.clone(),
+            outputs: vec![],
+            error_handling: ErrorHandling::default(),
+        })
+    }
+}
+
+// Data structures for templates
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct CreateTemplateResponse {
+    pub template_id: String,
+    pub template_name: String,
+    pub version: u32,
+    pub created: bool,
+    pub created_at: DateTime<Utc>,
+    pub validation_results: ValidationResults,
+    pub pattern_analysis: PatternAnalysisResult,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValidateTemplateResponse {
+    pub validation_results: ValidationResults,
+    /// JSON schema of the template's parameters
+    pub input_schema: Value,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetTemplatesParams {
+    pub category: Option<String>,
+    pub tag: Option<String>,
+    pub search: Option<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GetTemplatesResponse {
+    pub templates: Vec<WorkflowTemplateSummary>,
+    pub total_count: u32,
+    pub categories: Vec<String>,
+    pub tags: Vec<String>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowTemplate {
+    pub template_id: String,
+    /// The owning tenant
+    pub tenant_id: String,
+    pub name: String,
+    pub description: String,
+    pub category: String,
+    pub tags: Vec<String>,
+    pub is_public: bool,
+    /// Starts at 1 and goes up by one on every update
+    pub version: u32,
+    pub task_queue: String,
+    pub definition: TemplateDefinition,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct WorkflowTemplateSummary {
+    pub template_id: String,
+    pub tenant_id: String,
+    pub name: String,
+    pub description: String,
+    pub category: String,
+    pub tags: Vec<String>,
+    pub is_public: bool,
+    pub version: u32,
+    pub created_by: String,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub usage_count: u32,
+    pub success_rate: f64,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InstantiateTemplateRequest {
+    /// Prefix of the workflow id; defaults to `template`
+    pub workflow_name: Option<String>,
+    #[serde(default)]
+    pub parameters: Map<String, Value>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct InstantiateTemplateResponse {
+    pub workflow_id: String,
+    pub run_id: String,
+    pub template_id: String,
+    pub template_version: u32,
+    pub status: String,
+    /// Parameters the workflow was started with, defaults included
+    pub parameters: Map<String, Value>,
+    pub started_at: DateTime<Utc>,
+}
+
+/// A started template workflow, recorded in `workflow_executions`
+#[derive(Debug, Clone)]
+pub struct TemplateInstance {
+    pub tenant_id: String,
+    pub user_id: Option<String>,
+    pub template_id: String,
+    pub template_version: u32,
+    pub workflow_id: String,
+    pub run_id: String,
+    pub task_queue: String,
+    pub parameters: Value,
+    pub started_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UpdateTemplateResponse {
+    pub template_id: String,
+    pub updated: bool,
+    pub updated_at: DateTime<Utc>,
+    pub new_version: u32,
+    pub changes_summary: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct DeleteTemplateResponse {
+    pub template_id: String,
+    pub deleted: bool,
+    pub deleted_at: DateTime<Utc>,
+    pub affected_workflows: u32,
+    pub cleanup_performed: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PatternAnalysisParams {
+    pub tenant_id: Option<String>,
+    pub workflow_types: Option<Vec<String>>,
+    pub time_range: Option<(DateTime<Utc>, DateTime<Utc>)>,
+    pub min_occurrences: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct PatternAnalysisResponse {
+    pub analysis_id: String,
+    pub patterns_found: Vec<WorkflowPattern>,
+    pub template_suggestions: Vec<TemplateSuggestion>,
+    pub optimization_opportunities: Vec<String>,
+    pub reusability_score: f64,
+    pub analyzed_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct PatternAnalysisResult {
+    pub patterns_found: Vec<WorkflowPattern>,
+    pub template_suggestions: Vec<TemplateSuggestion>,
+    pub optimization_opportunities: Vec<String>,
+    pub reusability_score: f64,
+    pub confidence_score: f64,
+    pub extracted_parameters: Vec<TemplateParameter>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct WorkflowPattern {
+    pub pattern_type: PatternType,
+    pub description: String,
+    pub confidence: f64,
+    pub steps_involved: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub enum PatternType {
+    Sequential,
+    Parallel,
+    Conditional,
+    Loop,
+    ErrorHandling,
+    Compensation,
+    HumanApproval,
+}
+
+#[derive(Debug, Serialize, Deserialize, Clone)]
+pub struct TemplateSuggestion {
+    pub suggested_name: String,
+    pub description: String,
+    pub confidence: f64,
+    pub potential_savings: String,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GenerateTemplateRequest {
+    pub template_name: String,
+    pub description: String,
+    pub category: String,
+    pub tags: Vec<String>,
+    pub workflow_ids: Vec<String>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct GenerateTemplateResponse {
+    pub template_name: String,
+    pub generated: bool,
+    pub generated_at: DateTime<Utc>,
+    pub source_workflows: Vec<String>,
+    pub pattern_analysis: PatternAnalysisResult,
+    pub confidence_score: f64,
+    /// Template document to review and submit to the create endpoint
+    pub draft: TemplateDocument,
+    pub validation_results: ValidationResults,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateUsageResponse {
+    pub template_id: String,
+    pub total_uses: u32,
+    pub active_workflows: u32,
+    /// Percent of finished runs that completed
+    pub success_rate: f64,
+    pub average_duration_seconds: f64,
+    /// Runs started per day over the last 30 days
+    pub usage_trends: Vec<UsageTrend>,
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+/// Usage of a template by the requesting tenant
+#[derive(Debug, Serialize, Deserialize)]
+pub struct TemplateUsageStats {
+    pub total_uses: u32,
+    pub active_workflows: u32,
+    pub success_rate: f64,
+    pub average_duration_seconds: f64,
+    pub usage_trends: Vec<UsageTrend>,
+    pub last_used: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct UsageTrend {
+    pub date: DateTime<Utc>,
+    pub usage_count: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ValidationResults {
+    pub valid: bool,
+    pub warnings: Vec<String>,
+    pub errors: Vec<String>,
+}
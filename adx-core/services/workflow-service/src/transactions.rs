@@ -0,0 +1,559 @@
+//! Business transactions: operations that change several services and must
+//! either take effect everywhere or be undone everywhere.
+//!
+//! An operation is written as a sequence of typed steps. Each step makes
+//! its change through the service activities and knows how to undo it from
+//! its own output. The coordinator runs the steps in order; when one fails,
+//! the steps that completed are compensated newest first and the
+//! transaction fails with the original error.
+
+use crate::{
+    activities::*,
+    error::{WorkflowServiceError, WorkflowServiceResult},
+    workflows::get_default_permissions_for_role,
+};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::HashMap;
+use tracing::{info, warn};
+
+pub const BUSINESS_TRANSACTION_WORKFLOW_TYPE: &str = "business_transaction_workflow";
+
+/// Role given to the first user of an onboarded customer
+const ADMIN_ROLE: &str = "admin";
+
+/// One unit of a business transaction
+#[async_trait]
+pub trait TransactionStep: Send + Sync + 'static {
+    type Output: Clone + Serialize + Send + Sync;
+
+    /// Name the step is reported under
+    fn name(&self) -> &'static str;
+
+    async fn execute(&self, activities: &dyn CrossServiceActivities) -> WorkflowServiceResult<Self::Output>;
+
+    /// Undo the step after a later step failed
+    async fn compensate(&self, output: &Self::Output, activities: &dyn CrossServiceActivities) -> WorkflowServiceResult<()>;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TransactionStepStatus {
+    Completed,
+    Failed,
+    Compensated,
+    CompensationFailed,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TransactionStepRecord {
+    pub step: String,
+    pub status: TransactionStepStatus,
+    pub output: Option<Value>,
+    pub error: Option<String>,
+}
+
+/// A completed step together with what it needs to be undone
+#[async_trait]
+trait Compensation: Send + Sync {
+    async fn compensate(&self, activities: &dyn CrossServiceActivities) -> WorkflowServiceResult<()>;
+}
+
+struct CompletedStep<S: TransactionStep> {
+    step: S,
+    output: S::Output,
+}
+
+#[async_trait]
+impl<S: TransactionStep> Compensation for CompletedStep<S> {
+    async fn compensate(&self, activities: &dyn CrossServiceActivities) -> WorkflowServiceResult<()> {
+        self.step.compensate(&self.output, activities).await
+    }
+}
+
+/// Runs the steps of one transaction and undoes them on failure
+pub struct TransactionCoordinator<'a> {
+    activities: &'a dyn CrossServiceActivities,
+    steps: Vec<TransactionStepRecord>,
+    /// Completed steps with the index of their record, oldest first
+    completed: Vec<(usize, Box<dyn Compensation>)>,
+}
+
+impl<'a> TransactionCoordinator<'a> {
+    pub fn new(activities: &'a dyn CrossServiceActivities) -> Self {
+        Self {
+            activities,
+            steps: Vec::new(),
+            completed: Vec::new(),
+        }
+    }
+
+    /// Run a step and remember how to undo it
+    pub async fn run<S: TransactionStep>(&mut self, step: S) -> WorkflowServiceResult<S::Output> {
+        self.activities.checkpoint().await?;
+
+        match step.execute(self.activities).await {
+            Ok(output) => {
+                self.steps.push(TransactionStepRecord {
+                    step: step.name().to_string(),
+                    status: TransactionStepStatus::Completed,
+                    output: serde_json::to_value(&output).ok(),
+                    error: None,
+                });
+                self.completed.push((
+                    self.steps.len() - 1,
+                    Box::new(CompletedStep {
+                        step,
+                        output: output.clone(),
+                    }),
+                ));
+                Ok(output)
+            }
+            Err(error) => {
+                self.steps.push(TransactionStepRecord {
+                    step: step.name().to_string(),
+                    status: TransactionStepStatus::Failed,
+                    output: None,
+                    error: Some(error.to_string()),
+                });
+                Err(error)
+            }
+        }
+    }
+
+    /// Undo the completed steps newest first and report how many were
+    /// undone. Failures are recorded; compensation carries on regardless.
+    pub async fn compensate(&mut self) -> usize {
+        let mut compensated = 0;
+        while let Some((index, completed)) = self.completed.pop() {
+            let record = &mut self.steps[index];
+            match completed.compensate(self.activities).await {
+                Ok(()) => {
+                    record.status = TransactionStepStatus::Compensated;
+                    compensated += 1;
+                }
+                Err(error) => {
+                    warn!("Compensation of step {} failed: {}", record.step, error);
+                    record.status = TransactionStepStatus::CompensationFailed;
+                    record.error = Some(error.to_string());
+                }
+            }
+        }
+        compensated
+    }
+
+    pub fn into_steps(self) -> Vec<TransactionStepRecord> {
+        self.steps
+    }
+}
+
+/// A business operation and its parameters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "operation", content = "parameters", rename_all = "snake_case")]
+pub enum BusinessTransactionRequest {
+    /// New tenant with an admin user, a license and optional sample data
+    OnboardCustomer(OnboardCustomerRequest),
+}
+
+impl BusinessTransactionRequest {
+    pub fn operation(&self) -> &'static str {
+        match self {
+            Self::OnboardCustomer(_) => "onboard_customer",
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BusinessTransactionResult {
+    pub operation: String,
+    pub output: Value,
+    pub steps: Vec<TransactionStepRecord>,
+    pub completed_at: DateTime<Utc>,
+}
+
+/// Run a business operation as a single transaction
+pub async fn business_transaction_workflow(
+    request: BusinessTransactionRequest,
+    activities: &dyn CrossServiceActivities,
+) -> WorkflowServiceResult<BusinessTransactionResult> {
+    let operation = request.operation();
+    info!("Starting business transaction: {}", operation);
+
+    let mut coordinator = TransactionCoordinator::new(activities);
+    let outcome = match request {
+        BusinessTransactionRequest::OnboardCustomer(request) => onboard_customer(request, &mut coordinator)
+            .await
+            .and_then(|output| Ok(serde_json::to_value(output)?)),
+    };
+
+    match outcome {
+        Ok(output) => {
+            info!("Business transaction {} completed", operation);
+            Ok(BusinessTransactionResult {
+                operation: operation.to_string(),
+                output,
+                steps: coordinator.into_steps(),
+                completed_at: Utc::now(),
+            })
+        }
+        Err(error) => {
+            let to_undo = coordinator.completed.len();
+            let compensated = coordinator.compensate().await;
+            warn!(
+                "Business transaction {} failed; compensated {} of {} steps",
+                operation, compensated, to_undo
+            );
+            if matches!(error, WorkflowServiceError::WorkflowCancelled(_)) {
+                return Err(error);
+            }
+            Err(WorkflowServiceError::WorkflowExecution(format!(
+                "{} was rolled back ({} of {} steps undone): {}",
+                operation, compensated, to_undo, error
+            )))
+        }
+    }
+}
+
+// Onboard customer
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnboardCustomerRequest {
+    pub customer_name: String,
+    pub admin_email: String,
+    pub admin_name: String,
+    pub subscription_tier: String,
+    #[serde(default = "default_billing_cycle")]
+    pub billing_cycle: String,
+    #[serde(default)]
+    pub base_price: f64,
+    #[serde(default = "default_currency")]
+    pub currency: String,
+    #[serde(default)]
+    pub features: Vec<String>,
+    #[serde(default)]
+    pub create_sample_data: bool,
+}
+
+fn default_billing_cycle() -> String {
+    "Monthly".to_string()
+}
+
+fn default_currency() -> String {
+    "USD".to_string()
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OnboardCustomerResult {
+    pub tenant_id: String,
+    pub admin_user_id: String,
+    pub license_id: String,
+    pub sample_workspace_id: Option<String>,
+}
+
+impl OnboardCustomerRequest {
+    fn validate(&self) -> WorkflowServiceResult<()> {
+        if self.customer_name.trim().is_empty() || self.admin_name.trim().is_empty() {
+            return Err(WorkflowServiceError::Validation(
+                "customer_name and admin_name are required".to_string(),
+            ));
+        }
+        if !self.admin_email.contains('@') {
+            return Err(WorkflowServiceError::Validation("admin_email is not an email address".to_string()));
+        }
+        if self.base_price < 0.0 {
+            return Err(WorkflowServiceError::Validation("base_price cannot be negative".to_string()));
+        }
+        Ok(())
+    }
+}
+
+async fn onboard_customer(
+    request: OnboardCustomerRequest,
+    coordinator: &mut TransactionCoordinator<'_>,
+) -> WorkflowServiceResult<OnboardCustomerResult> {
+    request.validate()?;
+
+    let tenant = coordinator
+        .run(CreateTenantStep {
+            name: request.customer_name.clone(),
+            admin_email: request.admin_email.clone(),
+            subscription_tier: request.subscription_tier.clone(),
+            features: request.features.clone(),
+        })
+        .await?;
+
+    let admin = coordinator
+        .run(CreateAdminAccountStep {
+            tenant_id: tenant.tenant_id.clone(),
+            email: request.admin_email.clone(),
+            name: request.admin_name.clone(),
+        })
+        .await?;
+
+    coordinator
+        .run(CreateAdminProfileStep {
+            tenant_id: tenant.tenant_id.clone(),
+            user_id: admin.user_id.clone(),
+            email: request.admin_email.clone(),
+            name: request.admin_name.clone(),
+        })
+        .await?;
+
+    coordinator
+        .run(GrantAdminMembershipStep {
+            tenant_id: tenant.tenant_id.clone(),
+            user_id: admin.user_id.clone(),
+        })
+        .await?;
+
+    let license = coordinator
+        .run(ProvisionLicenseStep {
+            request: ProvisionLicenseRequest {
+                tenant_id: tenant.tenant_id.clone(),
+                subscription_tier: request.subscription_tier.clone(),
+                billing_cycle: request.billing_cycle.clone(),
+                base_price: request.base_price,
+                currency: request.currency.clone(),
+                features: request.features.clone(),
+            },
+        })
+        .await?;
+
+    let sample_workspace_id = if request.create_sample_data {
+        let workspace = coordinator
+            .run(SeedSampleDataStep {
+                tenant_id: tenant.tenant_id.clone(),
+                user_id: admin.user_id.clone(),
+            })
+            .await?;
+        Some(workspace.workspace_id)
+    } else {
+        None
+    };
+
+    Ok(OnboardCustomerResult {
+        tenant_id: tenant.tenant_id,
+        admin_user_id: admin.user_id,
+        license_id: license.license_id,
+        sample_workspace_id,
+    })
+}
+
+struct CreateTenantStep {
+    name: String,
+    admin_email: String,
+    subscription_tier: String,
+    features: Vec<String>,
+}
+
+#[async_trait]
+impl TransactionStep for CreateTenantStep {
+    type Output = CreateTenantResult;
+
+    fn name(&self) -> &'static str {
+        "create_tenant"
+    }
+
+    async fn execute(&self, activities: &dyn CrossServiceActivities) -> WorkflowServiceResult<Self::Output> {
+        activities
+            .create_tenant(CreateTenantRequest {
+                name: self.name.clone(),
+                admin_email: self.admin_email.clone(),
+                subscription_tier: self.subscription_tier.clone(),
+                features: self.features.clone(),
+            })
+            .await
+    }
+
+    async fn compensate(&self, output: &Self::Output, activities: &dyn CrossServiceActivities) -> WorkflowServiceResult<()> {
+        activities
+            .delete_tenant(DeleteTenantRequest {
+                tenant_id: output.tenant_id.clone(),
+            })
+            .await?;
+        Ok(())
+    }
+}
+
+struct CreateAdminAccountStep {
+    tenant_id: String,
+    email: String,
+    name: String,
+}
+
+#[async_trait]
+impl TransactionStep for CreateAdminAccountStep {
+    type Output = CreateUserAccountResult;
+
+    fn name(&self) -> &'static str {
+        "create_admin_account"
+    }
+
+    async fn execute(&self, activities: &dyn CrossServiceActivities) -> WorkflowServiceResult<Self::Output> {
+        activities
+            .create_user_account(CreateUserAccountRequest {
+                email: self.email.clone(),
+                name: self.name.clone(),
+                role: ADMIN_ROLE.to_string(),
+                tenant_id: self.tenant_id.clone(),
+                send_welcome_email: false,
+            })
+            .await
+    }
+
+    async fn compensate(&self, output: &Self::Output, activities: &dyn CrossServiceActivities) -> WorkflowServiceResult<()> {
+        activities
+            .delete_user_account(DeleteUserAccountRequest {
+                user_id: output.user_id.clone(),
+                tenant_id: self.tenant_id.clone(),
+            })
+            .await?;
+        Ok(())
+    }
+}
+
+struct CreateAdminProfileStep {
+    tenant_id: String,
+    user_id: String,
+    email: String,
+    name: String,
+}
+
+#[async_trait]
+impl TransactionStep for CreateAdminProfileStep {
+    type Output = CreateUserProfileResult;
+
+    fn name(&self) -> &'static str {
+        "create_admin_profile"
+    }
+
+    async fn execute(&self, activities: &dyn CrossServiceActivities) -> WorkflowServiceResult<Self::Output> {
+        let mut profile_data = HashMap::new();
+        profile_data.insert("name".to_string(), self.name.clone());
+        profile_data.insert("email".to_string(), self.email.clone());
+        profile_data.insert("role".to_string(), ADMIN_ROLE.to_string());
+
+        activities
+            .create_user_profile(CreateUserProfileRequest {
+                user_id: self.user_id.clone(),
+                tenant_id: self.tenant_id.clone(),
+                profile_data,
+                preferences: HashMap::new(),
+            })
+            .await
+    }
+
+    async fn compensate(&self, _output: &Self::Output, activities: &dyn CrossServiceActivities) -> WorkflowServiceResult<()> {
+        activities
+            .delete_user_data(DeleteUserDataRequest {
+                user_id: self.user_id.clone(),
+                tenant_id: self.tenant_id.clone(),
+                delete_options: HashMap::new(),
+            })
+            .await?;
+        Ok(())
+    }
+}
+
+struct GrantAdminMembershipStep {
+    tenant_id: String,
+    user_id: String,
+}
+
+impl GrantAdminMembershipStep {
+    fn membership(&self, active: bool) -> UpdateTenantUserMembershipRequest {
+        UpdateTenantUserMembershipRequest {
+            user_id: self.user_id.clone(),
+            tenant_id: self.tenant_id.clone(),
+            role: ADMIN_ROLE.to_string(),
+            permissions: if active { get_default_permissions_for_role(ADMIN_ROLE) } else { Vec::new() },
+            active,
+        }
+    }
+}
+
+#[async_trait]
+impl TransactionStep for GrantAdminMembershipStep {
+    type Output = UpdateTenantUserMembershipResult;
+
+    fn name(&self) -> &'static str {
+        "grant_admin_membership"
+    }
+
+    async fn execute(&self, activities: &dyn CrossServiceActivities) -> WorkflowServiceResult<Self::Output> {
+        activities.update_tenant_user_membership(self.membership(true)).await
+    }
+
+    async fn compensate(&self, _output: &Self::Output, activities: &dyn CrossServiceActivities) -> WorkflowServiceResult<()> {
+        activities.update_tenant_user_membership(self.membership(false)).await?;
+        Ok(())
+    }
+}
+
+struct ProvisionLicenseStep {
+    request: ProvisionLicenseRequest,
+}
+
+#[async_trait]
+impl TransactionStep for ProvisionLicenseStep {
+    type Output = ProvisionLicenseResult;
+
+    fn name(&self) -> &'static str {
+        "provision_license"
+    }
+
+    async fn execute(&self, activities: &dyn CrossServiceActivities) -> WorkflowServiceResult<Self::Output> {
+        activities.provision_license(self.request.clone()).await
+    }
+
+    async fn compensate(&self, output: &Self::Output, activities: &dyn CrossServiceActivities) -> WorkflowServiceResult<()> {
+        activities
+            .revoke_license(RevokeLicenseRequest {
+                tenant_id: self.request.tenant_id.clone(),
+                license_id: output.license_id.clone(),
+            })
+            .await?;
+        Ok(())
+    }
+}
+
+struct SeedSampleDataStep {
+    tenant_id: String,
+    user_id: String,
+}
+
+#[async_trait]
+impl TransactionStep for SeedSampleDataStep {
+    type Output = SetupUserFileWorkspaceResult;
+
+    fn name(&self) -> &'static str {
+        "seed_sample_data"
+    }
+
+    async fn execute(&self, activities: &dyn CrossServiceActivities) -> WorkflowServiceResult<Self::Output> {
+        let mut workspace_config = HashMap::new();
+        workspace_config.insert("type".to_string(), "default".to_string());
+        workspace_config.insert("sample_data".to_string(), "true".to_string());
+
+        activities
+            .setup_user_file_workspace(SetupUserFileWorkspaceRequest {
+                user_id: self.user_id.clone(),
+                tenant_id: self.tenant_id.clone(),
+                workspace_config,
+            })
+            .await
+    }
+
+    async fn compensate(&self, _output: &Self::Output, activities: &dyn CrossServiceActivities) -> WorkflowServiceResult<()> {
+        activities
+            .delete_user_files(DeleteUserFilesRequest {
+                user_id: self.user_id.clone(),
+                tenant_id: self.tenant_id.clone(),
+                delete_options: HashMap::new(),
+            })
+            .await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,803 @@
+//! Event-triggered workflows.
+//!
+//! Platform services publish events such as `file.uploaded` or
+//! `user.created`, and webhooks posted for a tenant arrive as
+//! `webhook.received` events. A trigger maps an event type, narrowed by an
+//! optional filter on the event, to a workflow start whose input is built
+//! from the event. Events are stored and queued on receipt; the dispatcher
+//! evaluates them in the background, so publishing never waits on Temporal.
+//! Each trigger starts at most one workflow per event.
+
+use crate::{
+    approvals::require_user,
+    batches::{names_only_tenant, validate_child_input},
+    config::WorkflowServiceConfig,
+    error::{WorkflowServiceError, WorkflowServiceResult},
+    monitoring::CostTracker,
+    repositories::WorkflowTriggerRepository,
+    task_queues::TaskQueueRouter,
+    templates::SUB_WORKFLOW_TYPES,
+};
+use adx_shared::temporal::{AdxTemporalClient, WorkflowSearchAttributes};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use serde_json::{json, Map, Value};
+use std::sync::Arc;
+use tokio::time::MissedTickBehavior;
+use tracing::{info, warn};
+use uuid::Uuid;
+
+/// Event type of webhooks received for a tenant
+pub const WEBHOOK_EVENT_TYPE: &str = "webhook.received";
+
+const MAX_NAME_LENGTH: usize = 255;
+const MAX_EVENT_TYPE_LENGTH: usize = 100;
+const MAX_EVENT_DATA_BYTES: usize = 256 * 1024;
+const DEFAULT_FIRINGS_SHOWN: u32 = 50;
+const MAX_FIRINGS_SHOWN: u32 = 200;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum TriggerEventStatus {
+    Pending,
+    Dispatched,
+    Failed,
+}
+
+impl TriggerEventStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            TriggerEventStatus::Pending => "pending",
+            TriggerEventStatus::Dispatched => "dispatched",
+            TriggerEventStatus::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> WorkflowServiceResult<Self> {
+        match value {
+            "pending" => Ok(TriggerEventStatus::Pending),
+            "dispatched" => Ok(TriggerEventStatus::Dispatched),
+            "failed" => Ok(TriggerEventStatus::Failed),
+            other => Err(WorkflowServiceError::Internal(format!("Unknown trigger event status: {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum FiringStatus {
+    Started,
+    Failed,
+}
+
+impl FiringStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            FiringStatus::Started => "started",
+            FiringStatus::Failed => "failed",
+        }
+    }
+
+    pub fn parse(value: &str) -> WorkflowServiceResult<Self> {
+        match value {
+            "started" => Ok(FiringStatus::Started),
+            "failed" => Ok(FiringStatus::Failed),
+            other => Err(WorkflowServiceError::Internal(format!("Unknown trigger firing status: {}", other))),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowTrigger {
+    pub trigger_id: String,
+    pub tenant_id: String,
+    pub name: String,
+    pub description: Option<String>,
+    /// An exact event type, or a prefix ending in `.*` such as `file.*`
+    pub event_type: String,
+    /// Event paths, such as `data.content_type`, and the values they must hold
+    pub filter: Map<String, Value>,
+    pub workflow_type: String,
+    /// Workflow input; `${event.<path>}` references are replaced from the event
+    pub input_template: Value,
+    pub enabled: bool,
+    pub created_by: Option<String>,
+    pub last_fired_at: Option<DateTime<Utc>>,
+    pub created_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+}
+
+/// A platform event as received, with its dispatch state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerEvent {
+    pub event_id: String,
+    pub tenant_id: String,
+    pub event_type: String,
+    /// Service or webhook source that sent the event
+    pub source: String,
+    /// What the event is about, such as a file or user id
+    pub subject: Option<String>,
+    pub data: Value,
+    pub status: TriggerEventStatus,
+    pub attempts: u32,
+    pub error: Option<String>,
+    pub occurred_at: DateTime<Utc>,
+    pub received_at: DateTime<Utc>,
+    pub dispatched_at: Option<DateTime<Utc>>,
+}
+
+/// A trigger's workflow start for one event
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerFiring {
+    pub firing_id: String,
+    pub tenant_id: String,
+    pub trigger_id: String,
+    pub event_id: String,
+    pub workflow_id: Option<String>,
+    pub status: FiringStatus,
+    pub error: Option<String>,
+    pub fired_at: DateTime<Utc>,
+}
+
+/// An event waiting for the dispatcher
+#[derive(Debug, Clone)]
+pub struct QueuedEvent {
+    pub event_id: String,
+    pub tenant_id: String,
+    /// Dispatch attempts including the current one
+    pub attempts: u32,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct CreateTriggerRequest {
+    pub name: String,
+    pub description: Option<String>,
+    pub event_type: String,
+    #[serde(default)]
+    pub filter: Map<String, Value>,
+    pub workflow_type: String,
+    pub input_template: Value,
+    #[serde(default = "default_enabled")]
+    pub enabled: bool,
+}
+
+fn default_enabled() -> bool {
+    true
+}
+
+/// Fields left out keep their current value
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct UpdateTriggerRequest {
+    pub name: Option<String>,
+    pub description: Option<String>,
+    pub event_type: Option<String>,
+    pub filter: Option<Map<String, Value>>,
+    pub workflow_type: Option<String>,
+    pub input_template: Option<Value>,
+    pub enabled: Option<bool>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ListTriggersResponse {
+    pub triggers: Vec<WorkflowTrigger>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+pub struct TriggerFiringParams {
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerFiringsResponse {
+    pub firings: Vec<TriggerFiring>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+pub struct PublishEventRequest {
+    /// Set by producers that may publish the same event twice; an event id
+    /// that was received before is ignored
+    pub event_id: Option<String>,
+    pub event_type: String,
+    pub source: String,
+    pub subject: Option<String>,
+    #[serde(default)]
+    pub data: Value,
+    /// Defaults to the time of receipt
+    pub occurred_at: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct PublishEventResponse {
+    pub event_id: String,
+    /// Whether the event was received before and not queued again
+    pub duplicate: bool,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TriggerEventDetails {
+    #[serde(flatten)]
+    pub event: TriggerEvent,
+    pub firings: Vec<TriggerFiring>,
+}
+
+pub struct WorkflowTriggerManager {
+    config: Arc<WorkflowServiceConfig>,
+    repository: Arc<dyn WorkflowTriggerRepository>,
+    temporal: AdxTemporalClient,
+    task_queues: Arc<TaskQueueRouter>,
+    costs: Arc<CostTracker>,
+}
+
+impl WorkflowTriggerManager {
+    pub fn new(
+        config: Arc<WorkflowServiceConfig>,
+        repository: Arc<dyn WorkflowTriggerRepository>,
+        temporal: AdxTemporalClient,
+        task_queues: Arc<TaskQueueRouter>,
+        costs: Arc<CostTracker>,
+    ) -> Self {
+        Self {
+            config,
+            repository,
+            temporal,
+            task_queues,
+            costs,
+        }
+    }
+
+    pub async fn create_trigger(
+        &self,
+        tenant_id: &str,
+        user_id: Option<&str>,
+        request: CreateTriggerRequest,
+    ) -> WorkflowServiceResult<WorkflowTrigger> {
+        let user_id = require_user(user_id)?;
+
+        let existing = self.repository.count_triggers(tenant_id).await?;
+        let max_triggers = self.config.triggers.max_triggers_per_tenant;
+        if existing >= max_triggers {
+            return Err(WorkflowServiceError::LimitExceeded(format!(
+                "Tenant already has {} of {} allowed triggers",
+                existing, max_triggers
+            )));
+        }
+
+        let now = Utc::now();
+        let trigger = WorkflowTrigger {
+            trigger_id: Uuid::new_v4().to_string(),
+            tenant_id: tenant_id.to_string(),
+            name: request.name.trim().to_string(),
+            description: request.description,
+            event_type: request.event_type,
+            filter: request.filter,
+            workflow_type: request.workflow_type,
+            input_template: request.input_template,
+            enabled: request.enabled,
+            created_by: Some(user_id.to_string()),
+            last_fired_at: None,
+            created_at: now,
+            updated_at: now,
+        };
+        validate_trigger(&trigger)?;
+        self.repository.create_trigger(&trigger).await?;
+
+        info!(
+            "Created trigger '{}' starting {} on {} for tenant {}",
+            trigger.name, trigger.workflow_type, trigger.event_type, tenant_id
+        );
+        Ok(trigger)
+    }
+
+    pub async fn list_triggers(&self, tenant_id: &str) -> WorkflowServiceResult<ListTriggersResponse> {
+        Ok(ListTriggersResponse {
+            triggers: self.repository.list_triggers(tenant_id).await?,
+        })
+    }
+
+    pub async fn get_trigger(&self, tenant_id: &str, trigger_id: &str) -> WorkflowServiceResult<WorkflowTrigger> {
+        self.repository
+            .get_trigger(tenant_id, trigger_id)
+            .await?
+            .ok_or_else(|| WorkflowServiceError::NotFound(format!("Trigger {} not found", trigger_id)))
+    }
+
+    /// Changes apply to events dispatched from now on, including events
+    /// already queued
+    pub async fn update_trigger(
+        &self,
+        tenant_id: &str,
+        user_id: Option<&str>,
+        trigger_id: &str,
+        request: UpdateTriggerRequest,
+    ) -> WorkflowServiceResult<WorkflowTrigger> {
+        let user_id = require_user(user_id)?;
+        let mut trigger = self.get_trigger(tenant_id, trigger_id).await?;
+
+        if let Some(name) = request.name {
+            trigger.name = name.trim().to_string();
+        }
+        if let Some(description) = request.description {
+            trigger.description = Some(description);
+        }
+        if let Some(event_type) = request.event_type {
+            trigger.event_type = event_type;
+        }
+        if let Some(filter) = request.filter {
+            trigger.filter = filter;
+        }
+        if let Some(workflow_type) = request.workflow_type {
+            trigger.workflow_type = workflow_type;
+        }
+        if let Some(input_template) = request.input_template {
+            trigger.input_template = input_template;
+        }
+        if let Some(enabled) = request.enabled {
+            trigger.enabled = enabled;
+        }
+        trigger.updated_at = Utc::now();
+        validate_trigger(&trigger)?;
+        self.repository.update_trigger(&trigger).await?;
+
+        info!("Trigger {} updated by {}", trigger_id, user_id);
+        Ok(trigger)
+    }
+
+    /// Workflows the trigger already started keep running
+    pub async fn delete_trigger(&self, tenant_id: &str, trigger_id: &str) -> WorkflowServiceResult<()> {
+        if !self.repository.delete_trigger(tenant_id, trigger_id).await? {
+            return Err(WorkflowServiceError::NotFound(format!("Trigger {} not found", trigger_id)));
+        }
+
+        info!("Deleted trigger {} for tenant {}", trigger_id, tenant_id);
+        Ok(())
+    }
+
+    /// The trigger's workflow starts, newest first
+    pub async fn list_firings(
+        &self,
+        tenant_id: &str,
+        trigger_id: &str,
+        params: TriggerFiringParams,
+    ) -> WorkflowServiceResult<TriggerFiringsResponse> {
+        self.get_trigger(tenant_id, trigger_id).await?;
+        let limit = params.limit.unwrap_or(DEFAULT_FIRINGS_SHOWN).clamp(1, MAX_FIRINGS_SHOWN);
+
+        Ok(TriggerFiringsResponse {
+            firings: self.repository.list_firings(tenant_id, trigger_id, limit).await?,
+        })
+    }
+
+    /// Store an event and queue it for the dispatcher
+    pub async fn publish_event(&self, tenant_id: &str, request: PublishEventRequest) -> WorkflowServiceResult<PublishEventResponse> {
+        validate_event_type(&request.event_type, false)?;
+        validate_source(&request.source)?;
+        let event_id = match request.event_id {
+            Some(event_id) => Uuid::parse_str(&event_id)
+                .map_err(|_| WorkflowServiceError::Validation(format!("Invalid event id: {}", event_id)))?
+                .to_string(),
+            None => Uuid::new_v4().to_string(),
+        };
+        let data = if request.data.is_null() { json!({}) } else { request.data };
+        if serde_json::to_vec(&data)?.len() > MAX_EVENT_DATA_BYTES {
+            return Err(WorkflowServiceError::Validation(format!(
+                "Event data is larger than {} bytes",
+                MAX_EVENT_DATA_BYTES
+            )));
+        }
+
+        let now = Utc::now();
+        let event = TriggerEvent {
+            event_id,
+            tenant_id: tenant_id.to_string(),
+            event_type: request.event_type,
+            source: request.source,
+            subject: request.subject,
+            data,
+            status: TriggerEventStatus::Pending,
+            attempts: 0,
+            error: None,
+            occurred_at: request.occurred_at.unwrap_or(now),
+            received_at: now,
+            dispatched_at: None,
+        };
+        let created = self.repository.record_event(&event).await?;
+
+        if created {
+            info!("Received {} event {} from {} for tenant {}", event.event_type, event.event_id, event.source, tenant_id);
+        }
+        Ok(PublishEventResponse {
+            event_id: event.event_id,
+            duplicate: !created,
+        })
+    }
+
+    /// A webhook posted for the tenant, received as a `webhook.received`
+    /// event from `source` with the body as its data
+    pub async fn receive_webhook(&self, tenant_id: &str, source: &str, body: Value) -> WorkflowServiceResult<PublishEventResponse> {
+        self.publish_event(
+            tenant_id,
+            PublishEventRequest {
+                event_id: None,
+                event_type: WEBHOOK_EVENT_TYPE.to_string(),
+                source: source.to_string(),
+                subject: None,
+                data: body,
+                occurred_at: None,
+            },
+        )
+        .await
+    }
+
+    pub async fn get_event(&self, tenant_id: &str, event_id: &str) -> WorkflowServiceResult<TriggerEventDetails> {
+        let event = self
+            .repository
+            .get_event(tenant_id, event_id)
+            .await?
+            .ok_or_else(|| WorkflowServiceError::NotFound(format!("Event {} not found", event_id)))?;
+        let firings = self.repository.event_firings(tenant_id, event_id).await?;
+
+        Ok(TriggerEventDetails { event, firings })
+    }
+
+    /// Run the dispatcher in the background for as long as the service runs
+    pub fn spawn_dispatcher(self: &Arc<Self>) {
+        let manager = self.clone();
+        let interval = self.config.triggers.dispatch_interval();
+        let batch_size = self.config.triggers.dispatch_batch_size as usize;
+
+        tokio::spawn(async move {
+            let mut ticker = tokio::time::interval(interval);
+            ticker.set_missed_tick_behavior(MissedTickBehavior::Delay);
+            loop {
+                ticker.tick().await;
+                // Keep going while full batches come back, so a burst of
+                // events doesn't wait a tick per batch
+                loop {
+                    match manager.dispatch_pending().await {
+                        Ok(claimed) if claimed >= batch_size => continue,
+                        Ok(_) => break,
+                        Err(error) => {
+                            warn!("Trigger dispatch failed: {}", error);
+                            break;
+                        }
+                    }
+                }
+            }
+        });
+    }
+
+    /// Claim a batch of queued events and start the workflows their
+    /// triggers call for. Returns how many events were claimed.
+    pub async fn dispatch_pending(&self) -> WorkflowServiceResult<usize> {
+        let triggers = &self.config.triggers;
+        let claimed = self
+            .repository
+            .claim_events(triggers.dispatch_batch_size, triggers.retry_delay_seconds)
+            .await?;
+
+        for queued in &claimed {
+            let Err(error) = self.dispatch(queued).await else {
+                self.repository
+                    .finish_event(queued, TriggerEventStatus::Dispatched, None)
+                    .await?;
+                continue;
+            };
+
+            if queued.attempts >= triggers.max_dispatch_attempts {
+                warn!(
+                    "Giving up on event {} for tenant {} after {} attempts: {}",
+                    queued.event_id, queued.tenant_id, queued.attempts, error
+                );
+                self.repository
+                    .finish_event(queued, TriggerEventStatus::Failed, Some(&error.to_string()))
+                    .await?;
+            } else {
+                warn!("Dispatching event {} failed, will retry: {}", queued.event_id, error);
+                let delay = triggers.retry_delay_seconds * queued.attempts as u64;
+                self.repository.retry_event(queued, delay, &error.to_string()).await?;
+            }
+        }
+
+        Ok(claimed.len())
+    }
+
+    /// Fire every enabled trigger the event matches and hasn't fired yet.
+    /// Starts that fail because of the trigger or the tenant are recorded as
+    /// failed firings; an error is returned only when a start may succeed
+    /// on a later attempt.
+    async fn dispatch(&self, queued: &QueuedEvent) -> WorkflowServiceResult<()> {
+        // Deleted together with its tenant; nothing left to do
+        let Some(event) = self.repository.get_event(&queued.tenant_id, &queued.event_id).await? else {
+            return Ok(());
+        };
+        let view = event_view(&event);
+
+        let mut retry_error = None;
+        for trigger in self.repository.enabled_triggers(&event.tenant_id).await? {
+            if !trigger_matches(&trigger, &event.event_type, &view) {
+                continue;
+            }
+            if let Err(error) = self.fire(&trigger, &event, &view).await {
+                warn!("Trigger {} failed to fire for event {}: {}", trigger.trigger_id, event.event_id, error);
+                retry_error = Some(error);
+            }
+        }
+
+        match retry_error {
+            Some(error) => Err(error),
+            None => Ok(()),
+        }
+    }
+
+    async fn fire(&self, trigger: &WorkflowTrigger, event: &TriggerEvent, view: &Value) -> WorkflowServiceResult<()> {
+        let workflow_id = format!("trigger_{}_{}", trigger.trigger_id, event.event_id);
+        let mut firing = TriggerFiring {
+            firing_id: Uuid::new_v4().to_string(),
+            tenant_id: event.tenant_id.clone(),
+            trigger_id: trigger.trigger_id.clone(),
+            event_id: event.event_id.clone(),
+            workflow_id: Some(workflow_id.clone()),
+            status: FiringStatus::Started,
+            error: None,
+            fired_at: Utc::now(),
+        };
+
+        let input = match self.prepare_start(trigger, event, view).await {
+            Ok(input) => input,
+            Err(error) => {
+                firing.workflow_id = None;
+                firing.status = FiringStatus::Failed;
+                firing.error = Some(error.to_string());
+                self.repository.claim_firing(&firing).await?;
+                return Ok(());
+            }
+        };
+
+        // Claim the firing before starting so a retried event can't start
+        // the workflow twice
+        if !self.repository.claim_firing(&firing).await? {
+            return Ok(());
+        }
+
+        let task_queue = match self.task_queues.route(&event.tenant_id, &trigger.workflow_type).await {
+            Ok(task_queue) => task_queue,
+            Err(error) => {
+                self.repository.release_firing(&event.tenant_id, &firing.firing_id).await?;
+                return Err(error);
+            }
+        };
+        if let Err(error) = self
+            .temporal
+            .start_workflow_with_search_attributes::<Value, Value>(
+                &trigger.workflow_type,
+                workflow_id.clone(),
+                &task_queue,
+                input,
+                &WorkflowSearchAttributes::new(&event.tenant_id, &trigger.workflow_type)
+                    .user(trigger.created_by.as_deref())
+                    .business_key(format!("trigger:{}", trigger.trigger_id))
+                    .business_key(format!("event:{}", event.event_id))
+                    .lane(self.task_queues.lane(&trigger.workflow_type, None)),
+            )
+            .await
+        {
+            self.repository.release_firing(&event.tenant_id, &firing.firing_id).await?;
+            return Err(WorkflowServiceError::Temporal(error.to_string()));
+        }
+
+        info!(
+            "Trigger {} started {} as {} for event {}",
+            trigger.trigger_id, trigger.workflow_type, workflow_id, event.event_id
+        );
+        Ok(())
+    }
+
+    /// The workflow input for the event, checked the way a direct start
+    /// would be
+    async fn prepare_start(&self, trigger: &WorkflowTrigger, event: &TriggerEvent, view: &Value) -> WorkflowServiceResult<Value> {
+        let input = render_template(&trigger.input_template, view)?;
+        validate_child_input(&trigger.workflow_type, &input)?;
+        if !names_only_tenant(&input, &event.tenant_id) {
+            return Err(WorkflowServiceError::Validation(
+                "Trigger input must name the event's tenant and no other".to_string(),
+            ));
+        }
+        self.costs.check_start(&event.tenant_id, &trigger.workflow_type).await?;
+        Ok(input)
+    }
+}
+
+fn validate_trigger(trigger: &WorkflowTrigger) -> WorkflowServiceResult<()> {
+    if trigger.name.is_empty() || trigger.name.len() > MAX_NAME_LENGTH {
+        return Err(WorkflowServiceError::Validation(format!(
+            "Trigger name must be between 1 and {} characters",
+            MAX_NAME_LENGTH
+        )));
+    }
+    validate_event_type(&trigger.event_type, true)?;
+    if !SUB_WORKFLOW_TYPES.contains(&trigger.workflow_type.as_str()) {
+        return Err(WorkflowServiceError::Validation(format!(
+            "Workflow type '{}' cannot be started by a trigger",
+            trigger.workflow_type
+        )));
+    }
+    if let Some(path) = trigger.filter.keys().find(|path| !valid_path(path)) {
+        return Err(WorkflowServiceError::Validation(format!("Invalid filter path '{}'", path)));
+    }
+    if !trigger.input_template.is_object() {
+        return Err(WorkflowServiceError::Validation("input_template must be an object".to_string()));
+    }
+    validate_references(&trigger.input_template)
+}
+
+/// Dot-separated lowercase segments such as `file.uploaded`; a trigger's
+/// event type may end in `.*` to match every type under a prefix
+fn validate_event_type(event_type: &str, allow_wildcard: bool) -> WorkflowServiceResult<()> {
+    let prefix = match event_type.strip_suffix(".*") {
+        Some(prefix) if allow_wildcard => prefix,
+        _ => event_type,
+    };
+    let valid = event_type.len() <= MAX_EVENT_TYPE_LENGTH
+        && !prefix.is_empty()
+        && prefix.split('.').all(|segment| {
+            !segment.is_empty()
+                && segment
+                    .chars()
+                    .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-')
+        });
+
+    if valid {
+        Ok(())
+    } else {
+        Err(WorkflowServiceError::Validation(format!("Invalid event type '{}'", event_type)))
+    }
+}
+
+fn validate_source(source: &str) -> WorkflowServiceResult<()> {
+    let valid = !source.is_empty()
+        && source.len() <= MAX_EVENT_TYPE_LENGTH
+        && source
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+
+    if valid {
+        Ok(())
+    } else {
+        Err(WorkflowServiceError::Validation(format!("Invalid event source '{}'", source)))
+    }
+}
+
+fn valid_path(path: &str) -> bool {
+    !path.is_empty() && path.split('.').all(|segment| !segment.is_empty())
+}
+
+/// Every `${...}` in the template has to reference the event
+fn validate_references(template: &Value) -> WorkflowServiceResult<()> {
+    match template {
+        Value::String(text) => {
+            for reference in references(text)? {
+                event_path(reference)?;
+            }
+            Ok(())
+        }
+        Value::Array(items) => items.iter().try_for_each(validate_references),
+        Value::Object(fields) => fields.values().try_for_each(validate_references),
+        _ => Ok(()),
+    }
+}
+
+/// The event as triggers see it: filters and templates address its fields
+/// by path, such as `subject` or `data.file_id`
+fn event_view(event: &TriggerEvent) -> Value {
+    json!({
+        "event_id": event.event_id,
+        "event_type": event.event_type,
+        "source": event.source,
+        "subject": event.subject,
+        "tenant_id": event.tenant_id,
+        "occurred_at": event.occurred_at.to_rfc3339(),
+        "data": event.data,
+    })
+}
+
+fn trigger_matches(trigger: &WorkflowTrigger, event_type: &str, view: &Value) -> bool {
+    let type_matches = match trigger.event_type.strip_suffix(".*") {
+        Some(prefix) => event_type
+            .strip_prefix(prefix)
+            .is_some_and(|rest| rest.starts_with('.')),
+        None => trigger.event_type == event_type,
+    };
+
+    type_matches
+        && trigger
+            .filter
+            .iter()
+            .all(|(path, expected)| lookup(view, path) == Some(expected))
+}
+
+fn lookup<'a>(value: &'a Value, path: &str) -> Option<&'a Value> {
+    path.split('.').try_fold(value, |value, segment| match value {
+        Value::Object(fields) => fields.get(segment),
+        Value::Array(items) => segment.parse::<usize>().ok().and_then(|index| items.get(index)),
+        _ => None,
+    })
+}
+
+/// The path a reference addresses within the event view
+fn event_path(reference: &str) -> WorkflowServiceResult<&str> {
+    reference
+        .strip_prefix("event.")
+        .filter(|path| valid_path(path))
+        .ok_or_else(|| {
+            WorkflowServiceError::Validation(format!(
+                "Invalid reference '${{{}}}': references take the form ${{event.<path>}}",
+                reference
+            ))
+        })
+}
+
+/// The `${...}` references in a string, in order
+fn references(text: &str) -> WorkflowServiceResult<Vec<&str>> {
+    let mut found = Vec::new();
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        let after = &rest[start + 2..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| WorkflowServiceError::Validation(format!("Unclosed reference in '{}'", text)))?;
+        found.push(after[..end].trim());
+        rest = &after[end + 1..];
+    }
+    Ok(found)
+}
+
+/// Replace references with values from the event. A string that is a single
+/// reference takes the referenced value as is; references inside longer
+/// strings are substituted as text.
+fn render_template(template: &Value, view: &Value) -> WorkflowServiceResult<Value> {
+    match template {
+        Value::String(text) => render_string(text, view),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| render_template(item, view))
+            .collect::<WorkflowServiceResult<Vec<_>>>()
+            .map(Value::Array),
+        Value::Object(fields) => fields
+            .iter()
+            .map(|(key, value)| Ok((key.clone(), render_template(value, view)?)))
+            .collect::<WorkflowServiceResult<Map<_, _>>>()
+            .map(Value::Object),
+        other => Ok(other.clone()),
+    }
+}
+
+fn render_string(text: &str, view: &Value) -> WorkflowServiceResult<Value> {
+    let resolve = |reference: &str| -> WorkflowServiceResult<&Value> {
+        let path = event_path(reference)?;
+        lookup(view, path)
+            .filter(|value| !value.is_null())
+            .ok_or_else(|| WorkflowServiceError::Validation(format!("Event has no value at '{}'", path)))
+    };
+
+    let trimmed = text.trim();
+    if trimmed.starts_with("${") && trimmed.ends_with('}') && trimmed.matches("${").count() == 1 {
+        return resolve(trimmed[2..trimmed.len() - 1].trim()).cloned();
+    }
+
+    let mut rendered = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find("${") {
+        rendered.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find('}')
+            .ok_or_else(|| WorkflowServiceError::Validation(format!("Unclosed reference in '{}'", text)))?;
+        match resolve(after[..end].trim())? {
+            Value::String(value) => rendered.push_str(value),
+            value => rendered.push_str(&value.to_string()),
+        }
+        rest = &after[end + 1..];
+    }
+    rendered.push_str(rest);
+    Ok(Value::String(rendered))
+}
@@ -1,509 +1,730 @@
+//! Workflow versions and version rollouts.
+//!
+//! Each workflow type has registered versions, one of which is the default
+//! that new runs start on. `workflow_executions` records the version a run
+//! started on, so runs still on an older or deprecated version can be found
+//! before that version's code is removed from the workers. Moving a type to
+//! a new version is a rollout with one of two strategies:
+//!
+//! - `patch`: the new version shares the old one's major version and guards
+//!   its changes with Temporal patch markers, so running executions can be
+//!   picked up by the new code. They are re-tagged to the new version.
+//! - `drain`: the new version is breaking. New runs start on it while runs
+//!   on the old version finish on the old code. The rollout completes once
+//!   none of them is left, and the old version is then sunset.
+
 use crate::{
-    config::WorkflowServiceConfig,
     error::{WorkflowServiceError, WorkflowServiceResult},
-    models::*,
+    repositories::WorkflowVersionRepository,
+    visualization::{closed_status, execution_status},
 };
+use adx_shared::temporal::{AdxTemporalClient, WorkflowVersion};
 use chrono::{DateTime, Utc};
 use serde::{Deserialize, Serialize};
-use std::{collections::HashMap, sync::Arc};
-use tracing::{info, warn, error};
+use std::{cmp::Ordering, collections::HashMap, sync::Arc};
+use tracing::{info, warn};
 use uuid::Uuid;
 
-/// Workflow versioning and migration management service
+const DEFAULT_BATCH_SIZE: u32 = 100;
+const MAX_BATCH_SIZE: u32 = 1000;
+const DEFAULT_OUTDATED_LIMIT: u32 = 100;
+const MAX_OUTDATED_LIMIT: u32 = 1000;
+
+/// Workflow versioning and rollout management service
 pub struct WorkflowVersionManager {
-    config: Arc<WorkflowServiceConfig>,
-    version_registry: Arc<VersionRegistry>,
-    migration_engine: Arc<MigrationEngine>,
-    compatibility_checker: Arc<CompatibilityChecker>,
+    repository: Arc<dyn WorkflowVersionRepository>,
+    temporal: AdxTemporalClient,
 }
 
 impl WorkflowVersionManager {
-    pub fn new(config: Arc<WorkflowServiceConfig>) -> Self {
-        let version_registry = Arc::new(VersionRegistry::new());
-        let migration_engine = Arc::new(MigrationEngine::new());
-        let compatibility_checker = Arc::new(CompatibilityChecker::new());
-
-        Self {
-            config,
-            version_registry,
-            migration_engine,
-            compatibility_checker,
-        }
+    pub fn new(repository: Arc<dyn WorkflowVersionRepository>, temporal: AdxTemporalClient) -> Self {
+        Self { repository, temporal }
     }
 
-    /// Register a new workflow version
+    /// Register a new workflow version. The first version of a type becomes
+    /// its default; later ones only when `make_default` is set.
     pub async fn register_workflow_version(&self, request: RegisterVersionRequest) -> WorkflowServiceResult<RegisterVersionResponse> {
         info!("Registering workflow version: {} v{}", request.workflow_type, request.version);
 
-        // Validate version format
-        self.validate_version_format(&request.version)?;
-
-        // Check compatibility with existing versions
-        let compatibility = self.compatibility_checker.check_compatibility(&request).await?;
+        let version = parse_version(&request.version)?;
+        let existing = self.repository.list_versions(&request.workflow_type).await?;
+        let current = existing.iter().find(|v| v.is_default);
+        let compatibility = check_compatibility(current, &version, &request.breaking_changes)?;
+
+        let make_default = current.is_none() || request.make_default;
+        if make_default && current.is_some() && !compatibility.is_compatible {
+            return Err(WorkflowServiceError::Validation(format!(
+                "Version {} is not compatible with the current default; register it and roll out with the drain strategy",
+                request.version
+            )));
+        }
 
-        // Register the version
-        let registration = self.version_registry.register_version(&request, compatibility).await?;
+        let record = WorkflowVersionRecord {
+            workflow_type: request.workflow_type.clone(),
+            version: version.to_string(),
+            status: if request.beta { VersionStatus::Beta } else { VersionStatus::Active },
+            is_default: false,
+            description: request.description,
+            schema: request.schema,
+            breaking_changes: request.breaking_changes.clone(),
+            migration_notes: request.migration_notes,
+            registered_at: Utc::now(),
+            deprecated_at: None,
+            deprecation_reason: None,
+            sunset_date: None,
+            migration_deadline: None,
+        };
+        self.repository.create_version(&record).await?;
+        if make_default {
+            self.repository.set_default(&record.workflow_type, &record.version).await?;
+        }
 
         Ok(RegisterVersionResponse {
             workflow_type: request.workflow_type,
-            version: request.version,
-            registered: registration.success,
-            registered_at: registration.registered_at,
-            compatibility_info: registration.compatibility_info,
-            migration_required: registration.migration_required,
-            breaking_changes: registration.breaking_changes,
+            version: record.version,
+            registered: true,
+            registered_at: record.registered_at,
+            is_default: make_default,
+            migration_required: compatibility.migration_required,
+            compatibility_info: compatibility,
+            breaking_changes: request.breaking_changes,
         })
     }
 
-    /// Get available versions for a workflow type
+    /// Registered versions, newest first, with the runs still on each
     pub async fn get_workflow_versions(&self, workflow_type: &str) -> WorkflowServiceResult<WorkflowVersionsResponse> {
-        info!("Getting versions for workflow type: {}", workflow_type);
-
-        let versions = self.version_registry.get_versions(workflow_type).await?;
+        let versions = self.sorted_versions(workflow_type).await?;
+        if versions.is_empty() {
+            return Err(WorkflowServiceError::NotFound(format!(
+                "No versions registered for {}",
+                workflow_type
+            )));
+        }
+        let running = self.repository.running_counts(workflow_type).await?;
 
         Ok(WorkflowVersionsResponse {
             workflow_type: workflow_type.to_string(),
-            versions,
-            current_version: self.version_registry.get_current_version(workflow_type).await?,
-            deprecated_versions: self.version_registry.get_deprecated_versions(workflow_type).await?,
+            current_version: versions.iter().find(|v| v.is_default).map(|v| v.version.clone()),
+            deprecated_versions: versions
+                .iter()
+                .filter(|v| matches!(v.status, VersionStatus::Deprecated | VersionStatus::Sunset))
+                .map(|v| v.version.clone())
+                .collect(),
+            versions: versions
+                .into_iter()
+                .map(|v| WorkflowVersionInfo {
+                    active_workflows: running.get(&v.version).copied().unwrap_or(0),
+                    version: v.version,
+                    status: v.status,
+                    is_default: v.is_default,
+                    registered_at: v.registered_at,
+                    deprecated_at: v.deprecated_at,
+                    sunset_date: v.sunset_date,
+                    migration_deadline: v.migration_deadline,
+                    description: v.description,
+                    breaking_changes: v.breaking_changes,
+                })
+                .collect(),
+        })
+    }
+
+    /// Runs still on a version other than the default that is older than it
+    /// or deprecated. Candidates are checked against Temporal first, and
+    /// runs that have closed there are marked closed.
+    pub async fn find_outdated_executions(
+        &self,
+        workflow_type: &str,
+        params: OutdatedExecutionsParams,
+    ) -> WorkflowServiceResult<OutdatedExecutionsResponse> {
+        let versions = self.sorted_versions(workflow_type).await?;
+        let default = versions.iter().find(|v| v.is_default).ok_or_else(|| {
+            WorkflowServiceError::NotFound(format!("No default version registered for {}", workflow_type))
+        })?;
+        let default_version = parse_version(&default.version)?;
+        let limit = params.limit.unwrap_or(DEFAULT_OUTDATED_LIMIT).clamp(1, MAX_OUTDATED_LIMIT);
+        let now = Utc::now();
+
+        let mut outdated_versions = Vec::new();
+        let mut executions = Vec::new();
+        let mut unverified = 0;
+        for record in &versions {
+            let outdated = !record.is_default
+                && (default_version.is_newer_than(&parse_version(&record.version)?)
+                    || matches!(record.status, VersionStatus::Deprecated | VersionStatus::Sunset));
+            if !outdated {
+                continue;
+            }
+
+            let remaining = limit.saturating_sub(executions.len() as u32);
+            if remaining > 0 {
+                let reconciled = self
+                    .reconcile_running(workflow_type, &record.version, None, remaining)
+                    .await?;
+                unverified += reconciled.unverified;
+                executions.extend(reconciled.running);
+            }
+            let running = self.repository.running_counts(workflow_type).await?;
+
+            outdated_versions.push(OutdatedVersion {
+                version: record.version.clone(),
+                status: record.status,
+                running: running.get(&record.version).copied().unwrap_or(0),
+                migration_deadline: record.migration_deadline,
+                past_deadline: record.migration_deadline.is_some_and(|deadline| deadline <= now),
+            });
+        }
+
+        Ok(OutdatedExecutionsResponse {
+            workflow_type: workflow_type.to_string(),
+            default_version: default.version.clone(),
+            versions: outdated_versions,
+            executions,
+            unverified,
         })
     }
 
-    /// Migrate workflows to a new version
+    /// Start a rollout from one version to a newer one. The new version
+    /// becomes the default either way; a patch rollout also moves running
+    /// executions onto it, while a drain rollout waits for them to finish.
     pub async fn migrate_workflows(&self, request: MigrateWorkflowsRequest) -> WorkflowServiceResult<MigrateWorkflowsResponse> {
-        info!("Migrating workflows from {} v{} to v{}", 
+        info!("Migrating workflows from {} v{} to v{}",
             request.workflow_type, request.from_version, request.to_version);
 
-        // Validate migration path
-        let migration_plan = self.create_migration_plan(&request).await?;
+        let from = self.require_version(&request.workflow_type, &request.from_version).await?;
+        let to = self.require_version(&request.workflow_type, &request.to_version).await?;
+        let from_version = parse_version(&from.version)?;
+        let to_version = parse_version(&to.version)?;
+        if !to_version.is_newer_than(&from_version) {
+            return Err(WorkflowServiceError::Validation(format!(
+                "Version {} is not newer than {}",
+                to.version, from.version
+            )));
+        }
+        if matches!(to.status, VersionStatus::Deprecated | VersionStatus::Sunset) {
+            return Err(WorkflowServiceError::Validation(format!(
+                "Cannot roll out to {} version {}",
+                to.status.as_str(),
+                to.version
+            )));
+        }
+
+        let compatible = to_version.major == from_version.major;
+        let strategy = request.strategy.unwrap_or(if compatible {
+            RolloutStrategy::Patch
+        } else {
+            RolloutStrategy::Drain
+        });
+        if strategy == RolloutStrategy::Patch && !compatible {
+            return Err(WorkflowServiceError::Validation(format!(
+                "Version {} changes the major version; running executions can only be drained",
+                to.version
+            )));
+        }
+        if strategy == RolloutStrategy::Drain && request.workflow_ids.is_some() {
+            return Err(WorkflowServiceError::Validation(
+                "A drain applies to every run on the version; workflow_ids only apply to patch rollouts".to_string(),
+            ));
+        }
+        let batch_size = request.batch_size.unwrap_or(DEFAULT_BATCH_SIZE).clamp(1, MAX_BATCH_SIZE);
 
-        // Execute migration
-        let migration_result = self.migration_engine.execute_migration(&migration_plan).await?;
+        let now = Utc::now();
+        let mut rollout = VersionRollout {
+            migration_id: Uuid::new_v4().to_string(),
+            workflow_type: request.workflow_type.clone(),
+            from_version: from.version.clone(),
+            to_version: to.version.clone(),
+            strategy,
+            status: MigrationStatus::Running,
+            dry_run: request.dry_run.unwrap_or(false),
+            rollback_enabled: request.enable_rollback.unwrap_or(true),
+            batch_size,
+            total_workflows: 0,
+            migrated_workflow_ids: Vec::new(),
+            failed_migrations: 0,
+            remaining_workflows: 0,
+            errors: Vec::new(),
+            started_at: now,
+            updated_at: now,
+            completed_at: None,
+            rollback_reason: None,
+        };
 
-        Ok(MigrateWorkflowsResponse {
-            migration_id: migration_result.migration_id,
-            workflow_type: request.workflow_type,
-            from_version: request.from_version,
-            to_version: request.to_version,
-            total_workflows: migration_result.total_workflows,
-            migrated_workflows: migration_result.migrated_workflows,
-            failed_migrations: migration_result.failed_migrations,
-            migration_status: migration_result.status,
-            started_at: migration_result.started_at,
-            completed_at: migration_result.completed_at,
-            errors: migration_result.errors,
-        })
+        let running = self
+            .reconcile_all(&rollout.workflow_type, &rollout.from_version, request.workflow_ids.as_deref())
+            .await?;
+        rollout.total_workflows = running.len() as u32;
+        rollout.remaining_workflows = rollout.total_workflows;
+
+        if let Some(workflow_ids) = &request.workflow_ids {
+            for workflow_id in workflow_ids {
+                if !running.iter().any(|execution| &execution.workflow_id == workflow_id) {
+                    rollout.errors.push(format!(
+                        "{} is not running on version {}",
+                        workflow_id, rollout.from_version
+                    ));
+                    rollout.failed_migrations += 1;
+                }
+            }
+        }
+
+        if rollout.dry_run {
+            rollout.status = MigrationStatus::Completed;
+            rollout.completed_at = Some(Utc::now());
+            self.repository.create_rollout(&rollout).await?;
+            return Ok(rollout.into());
+        }
+
+        self.repository.create_rollout(&rollout).await?;
+        self.repository.set_default(&rollout.workflow_type, &rollout.to_version).await?;
+
+        match strategy {
+            RolloutStrategy::Patch => {
+                let workflow_ids: Vec<String> = running.iter().map(|e| e.workflow_id.clone()).collect();
+                for batch in workflow_ids.chunks(batch_size as usize) {
+                    match self
+                        .repository
+                        .retag_executions(&rollout.workflow_type, &rollout.from_version, &rollout.to_version, batch)
+                        .await
+                    {
+                        Ok(moved) => {
+                            rollout.failed_migrations += (batch.len() - moved.len()) as u32;
+                            rollout.migrated_workflow_ids.extend(moved);
+                        }
+                        Err(error) => {
+                            warn!("Failed to migrate a batch of rollout {}: {}", rollout.migration_id, error);
+                            rollout.failed_migrations += batch.len() as u32;
+                            rollout.errors.push(error.to_string());
+                        }
+                    }
+                }
+                rollout.remaining_workflows = 0;
+                rollout.status = if rollout.migrated_workflow_ids.is_empty() && rollout.failed_migrations > 0 {
+                    MigrationStatus::Failed
+                } else {
+                    MigrationStatus::Completed
+                };
+                rollout.completed_at = Some(Utc::now());
+            }
+            RolloutStrategy::Drain => {
+                if rollout.remaining_workflows == 0 {
+                    self.finish_drain(&mut rollout).await?;
+                }
+            }
+        }
+
+        rollout.updated_at = Utc::now();
+        self.repository.update_rollout(&rollout).await?;
+        info!(
+            "Rollout {} of {} from {} to {} ({}): {} running, {} migrated",
+            rollout.migration_id,
+            rollout.workflow_type,
+            rollout.from_version,
+            rollout.to_version,
+            rollout.strategy.as_str(),
+            rollout.total_workflows,
+            rollout.migrated_workflow_ids.len()
+        );
+
+        Ok(rollout.into())
     }
 
-    /// Get migration status
+    /// A rollout's progress. Draining rollouts re-check the runs left on the
+    /// old version and complete once there are none.
     pub async fn get_migration_status(&self, migration_id: &str) -> WorkflowServiceResult<MigrationStatusResponse> {
-        info!("Getting migration status for: {}", migration_id);
+        let mut rollout = self.require_rollout(migration_id).await?;
 
-        let status = self.migration_engine.get_migration_status(migration_id).await?;
+        if rollout.strategy == RolloutStrategy::Drain && rollout.status == MigrationStatus::Running {
+            let running = self.reconcile_all(&rollout.workflow_type, &rollout.from_version, None).await?;
+            rollout.remaining_workflows = running.len() as u32;
+            if rollout.remaining_workflows == 0 {
+                self.finish_drain(&mut rollout).await?;
+            }
+            rollout.updated_at = Utc::now();
+            self.repository.update_rollout(&rollout).await?;
+        }
 
+        let processed = rollout.total_workflows.saturating_sub(rollout.remaining_workflows);
         Ok(MigrationStatusResponse {
-            migration_id: migration_id.to_string(),
-            status: status.status,
-            progress: status.progress,
-            total_workflows: status.total_workflows,
-            processed_workflows: status.processed_workflows,
-            successful_migrations: status.successful_migrations,
-            failed_migrations: status.failed_migrations,
-            current_batch: status.current_batch,
-            estimated_completion: status.estimated_completion,
-            errors: status.errors,
-            started_at: status.started_at,
-            updated_at: status.updated_at,
+            progress: if rollout.total_workflows == 0 {
+                100.0
+            } else {
+                processed as f64 / rollout.total_workflows as f64 * 100.0
+            },
+            processed_workflows: processed,
+            successful_migrations: rollout.migrated_workflow_ids.len() as u32,
+            migration_id: rollout.migration_id,
+            workflow_type: rollout.workflow_type,
+            from_version: rollout.from_version,
+            to_version: rollout.to_version,
+            strategy: rollout.strategy,
+            status: rollout.status,
+            dry_run: rollout.dry_run,
+            total_workflows: rollout.total_workflows,
+            remaining_workflows: rollout.remaining_workflows,
+            failed_migrations: rollout.failed_migrations,
+            errors: rollout.errors,
+            started_at: rollout.started_at,
+            updated_at: rollout.updated_at,
+            completed_at: rollout.completed_at,
         })
     }
 
-    /// Rollback a migration
+    /// Make the old version the default again. Runs a patch rollout moved
+    /// are moved back if they are still running; runs that started on the
+    /// new version stay on it.
     pub async fn rollback_migration(&self, request: RollbackMigrationRequest) -> WorkflowServiceResult<RollbackMigrationResponse> {
         warn!("Rolling back migration: {} with reason: {}", request.migration_id, request.reason);
 
-        let rollback_result = self.migration_engine.rollback_migration(&request).await?;
+        let mut rollout = self.require_rollout(&request.migration_id).await?;
+        if rollout.dry_run {
+            return Err(WorkflowServiceError::Validation("A dry run has nothing to roll back".to_string()));
+        }
+        if !rollout.rollback_enabled {
+            return Err(WorkflowServiceError::Validation(format!(
+                "Rollout {} was started without rollback",
+                rollout.migration_id
+            )));
+        }
+        if rollout.status == MigrationStatus::RolledBack {
+            return Err(WorkflowServiceError::Validation(format!(
+                "Rollout {} has already been rolled back",
+                rollout.migration_id
+            )));
+        }
+
+        let started_at = Utc::now();
+        let from = self.require_version(&rollout.workflow_type, &rollout.from_version).await?;
+        if matches!(from.status, VersionStatus::Deprecated | VersionStatus::Sunset) {
+            self.repository
+                .set_status(&rollout.workflow_type, &rollout.from_version, VersionStatus::Active)
+                .await?;
+        }
+        self.repository.set_default(&rollout.workflow_type, &rollout.from_version).await?;
+
+        let mut rolled_back = Vec::new();
+        for batch in rollout.migrated_workflow_ids.chunks(rollout.batch_size.max(1) as usize) {
+            rolled_back.extend(
+                self.repository
+                    .retag_executions(&rollout.workflow_type, &rollout.to_version, &rollout.from_version, batch)
+                    .await?,
+            );
+        }
+
+        rollout.status = MigrationStatus::RolledBack;
+        rollout.rollback_reason = Some(request.reason);
+        rollout.updated_at = Utc::now();
+        rollout.completed_at = Some(rollout.updated_at);
+        self.repository.update_rollout(&rollout).await?;
 
         Ok(RollbackMigrationResponse {
-            migration_id: request.migration_id,
-            rollback_id: rollback_result.rollback_id,
-            rolled_back: rollback_result.success,
-            rollback_started_at: rollback_result.started_at,
-            rollback_completed_at: rollback_result.completed_at,
-            workflows_rolled_back: rollback_result.workflows_rolled_back,
-            message: rollback_result.message,
+            migration_id: rollout.migration_id,
+            rolled_back: true,
+            rollback_started_at: started_at,
+            rollback_completed_at: rollout.completed_at,
+            workflows_rolled_back: rolled_back.len() as u32,
+            message: format!(
+                "{} is the default version of {} again",
+                rollout.from_version, rollout.workflow_type
+            ),
         })
     }
 
-    /// Deprecate a workflow version
+    /// Mark a version deprecated. The default version can't be deprecated;
+    /// roll out its successor first.
     pub async fn deprecate_version(&self, request: DeprecateVersionRequest) -> WorkflowServiceResult<DeprecateVersionResponse> {
         info!("Deprecating workflow version: {} v{}", request.workflow_type, request.version);
 
-        let deprecation_result = self.version_registry.deprecate_version(&request).await?;
+        let version = self.require_version(&request.workflow_type, &request.version).await?;
+        if version.is_default {
+            return Err(WorkflowServiceError::Validation(format!(
+                "{} is the default version of {}; roll out a newer version first",
+                version.version, request.workflow_type
+            )));
+        }
+        if let (Some(deadline), Some(sunset)) = (request.migration_deadline, request.sunset_date) {
+            if deadline > sunset {
+                return Err(WorkflowServiceError::Validation(
+                    "migration_deadline must not be after sunset_date".to_string(),
+                ));
+            }
+        }
+
+        let deprecated_at = Utc::now();
+        self.repository
+            .deprecate(
+                &request.workflow_type,
+                &version.version,
+                &request.reason,
+                request.sunset_date,
+                request.migration_deadline,
+            )
+            .await?;
+        let running = self.repository.running_counts(&request.workflow_type).await?;
 
         Ok(DeprecateVersionResponse {
             workflow_type: request.workflow_type,
-            version: request.version,
-            deprecated: deprecation_result.success,
-            deprecated_at: deprecation_result.deprecated_at,
+            version: version.version.clone(),
+            deprecated: true,
+            deprecated_at,
             sunset_date: request.sunset_date,
             migration_deadline: request.migration_deadline,
-            affected_workflows: deprecation_result.affected_workflows,
+            affected_workflows: running.get(&version.version).copied().unwrap_or(0),
         })
     }
 
-    /// Get version compatibility matrix
+    /// Compatibility between consecutive versions, and how each older
+    /// version reaches the default
     pub async fn get_compatibility_matrix(&self, workflow_type: &str) -> WorkflowServiceResult<CompatibilityMatrixResponse> {
-        info!("Getting compatibility matrix for workflow type: {}", workflow_type);
-
-        let matrix = self.compatibility_checker.get_compatibility_matrix(workflow_type).await?;
+        let mut versions = self.sorted_versions(workflow_type).await?;
+        if versions.is_empty() {
+            return Err(WorkflowServiceError::NotFound(format!(
+                "No versions registered for {}",
+                workflow_type
+            )));
+        }
+        versions.reverse();
+        let parsed = versions
+            .iter()
+            .map(|v| parse_version(&v.version))
+            .collect::<WorkflowServiceResult<Vec<_>>>()?;
+        let running = self.repository.running_counts(workflow_type).await?;
+
+        let compatibility_matrix = versions
+            .windows(2)
+            .zip(parsed.windows(2))
+            .map(|(pair, parsed)| {
+                let level = if parsed[1].major == parsed[0].major {
+                    CompatibilityLevel::Backward
+                } else {
+                    CompatibilityLevel::None
+                };
+                (pair[0].version.clone(), pair[1].version.clone(), level)
+            })
+            .collect();
+
+        let breaking_changes = versions
+            .iter()
+            .filter(|v| !v.breaking_changes.is_empty())
+            .map(|v| (v.version.clone(), v.breaking_changes.clone()))
+            .collect();
+
+        let mut migration_paths = Vec::new();
+        let mut recommendations = Vec::new();
+        if let Some(target) = versions.iter().position(|v| v.is_default) {
+            for (index, version) in versions.iter().enumerate().take(target) {
+                let running_on = running.get(&version.version).copied().unwrap_or(0);
+                if running_on == 0 && version.status == VersionStatus::Sunset {
+                    continue;
+                }
+                // The last version of each major in between
+                let intermediate_versions: Vec<String> = (index + 1..target)
+                    .filter(|&i| parsed[i].major != parsed[index].major && parsed[i].major != parsed[i + 1].major)
+                    .map(|i| versions[i].version.clone())
+                    .collect();
+                let complexity = match parsed[target].major - parsed[index].major {
+                    0 if parsed[target].minor == parsed[index].minor => MigrationComplexity::Low,
+                    0 => MigrationComplexity::Medium,
+                    1 => MigrationComplexity::High,
+                    _ => MigrationComplexity::Critical,
+                };
+                migration_paths.push(MigrationPath {
+                    from_version: version.version.clone(),
+                    to_version: versions[target].version.clone(),
+                    direct_migration: parsed[target].major == parsed[index].major,
+                    intermediate_versions,
+                    complexity,
+                });
+
+                if running_on > 0 {
+                    let strategy = if parsed[target].major == parsed[index].major { "patch" } else { "drain" };
+                    recommendations.push(format!(
+                        "{} still has {} running; roll out to {} with the {} strategy",
+                        version.version, running_on, versions[target].version, strategy
+                    ));
+                } else if version.status != VersionStatus::Sunset {
+                    recommendations.push(format!(
+                        "No runs are left on {}; it can be deprecated and its code removed",
+                        version.version
+                    ));
+                }
+            }
+        }
 
         Ok(CompatibilityMatrixResponse {
             workflow_type: workflow_type.to_string(),
-            compatibility_matrix: matrix.compatibility_matrix,
-            breaking_changes: matrix.breaking_changes,
-            migration_paths: matrix.migration_paths,
-            recommendations: matrix.recommendations,
+            compatibility_matrix,
+            breaking_changes,
+            migration_paths,
+            recommendations,
         })
     }
 
     // Private helper methods
 
-    fn validate_version_format(&self, version: &str) -> WorkflowServiceResult<()> {
-        // Validate semantic versioning format (e.g., "1.2.3")
-        let parts: Vec<&str> = version.split('.').collect();
-        if parts.len() != 3 {
-            return Err(WorkflowServiceError::InvalidVersion(
-                format!("Version must be in format 'major.minor.patch', got: {}", version)
-            ));
-        }
-
-        for part in parts {
-            if part.parse::<u32>().is_err() {
-                return Err(WorkflowServiceError::InvalidVersion(
-                    format!("Version parts must be numeric, got: {}", version)
-                ));
-            }
-        }
-
-        Ok(())
+    /// Registered versions, newest first
+    async fn sorted_versions(&self, workflow_type: &str) -> WorkflowServiceResult<Vec<WorkflowVersionRecord>> {
+        let mut versions = self
+            .repository
+            .list_versions(workflow_type)
+            .await?
+            .into_iter()
+            .map(|record| Ok((parse_version(&record.version)?, record)))
+            .collect::<WorkflowServiceResult<Vec<_>>>()?;
+        versions.sort_by(|(a, _), (b, _)| compare_versions(b, a));
+        Ok(versions.into_iter().map(|(_, record)| record).collect())
     }
 
-    async fn create_migration_plan(&self, request: &MigrateWorkflowsRequest) -> WorkflowServiceResult<MigrationPlan> {
-        // Analyze differences between versions
-        let version_diff = self.version_registry.compare_versions(
-            &request.workflow_type,
-            &request.from_version,
-            &request.to_version,
-        ).await?;
-
-        // Create migration steps
-        let migration_steps = self.create_migration_steps(&version_diff)?;
-
-        // Estimate migration complexity and time
-        let complexity = self.estimate_migration_complexity(&version_diff);
-        let estimated_duration = self.estimate_migration_duration(&request, &complexity);
-
-        Ok(MigrationPlan {
-            migration_id: Uuid::new_v4().to_string(),
-            workflow_type: request.workflow_type.clone(),
-            from_version: request.from_version.clone(),
-            to_version: request.to_version.clone(),
-            migration_steps,
-            complexity,
-            estimated_duration,
-            batch_size: request.batch_size.unwrap_or(100),
-            dry_run: request.dry_run.unwrap_or(false),
-            rollback_enabled: request.enable_rollback.unwrap_or(true),
-        })
+    async fn require_version(&self, workflow_type: &str, version: &str) -> WorkflowServiceResult<WorkflowVersionRecord> {
+        let version = parse_version(version)?.to_string();
+        self.repository
+            .get_version(workflow_type, &version)
+            .await?
+            .ok_or_else(|| WorkflowServiceError::NotFound(format!("Version {} of {} is not registered", version, workflow_type)))
     }
 
-    fn create_migration_steps(&self, version_diff: &VersionDiff) -> WorkflowServiceResult<Vec<MigrationStep>> {
-        let mut steps = Vec::new();
-
-        // Add schema migration steps
-        if !version_diff.schema_changes.is_empty() {
-            steps.push(MigrationStep {
-                step_id: "schema_migration".to_string(),
-                step_type: MigrationStepType::SchemaUpdate,
-                description: "Update workflow schema".to_string(),
-                required: true,
-                rollback_supported: true,
-                estimated_duration: std::time::Duration::from_secs(30),
-            });
-        }
-
-        // Add data transformation steps
-        if !version_diff.data_transformations.is_empty() {
-            steps.push(MigrationStep {
-                step_id: "data_transformation".to_string(),
-                step_type: MigrationStepType::DataTransformation,
-                description: "Transform workflow data".to_string(),
-                required: true,
-                rollback_supported: true,
-                estimated_duration: std::time::Duration::from_secs(120),
-            });
-        }
-
-        // Add activity mapping steps
-        if !version_diff.activity_changes.is_empty() {
-            steps.push(MigrationStep {
-                step_id: "activity_mapping".to_string(),
-                step_type: MigrationStepType::ActivityMapping,
-                description: "Map activities to new version".to_string(),
-                required: true,
-                rollback_supported: false,
-                estimated_duration: std::time::Duration::from_secs(60),
-            });
-        }
-
-        // Add validation step
-        steps.push(MigrationStep {
-            step_id: "validation".to_string(),
-            step_type: MigrationStepType::Validation,
-            description: "Validate migrated workflows".to_string(),
-            required: true,
-            rollback_supported: false,
-            estimated_duration: std::time::Duration::from_secs(45),
-        });
-
-        Ok(steps)
+    async fn require_rollout(&self, migration_id: &str) -> WorkflowServiceResult<VersionRollout> {
+        self.repository
+            .get_rollout(migration_id)
+            .await?
+            .ok_or_else(|| WorkflowServiceError::NotFound(format!("Migration {} not found", migration_id)))
     }
 
-    fn estimate_migration_complexity(&self, version_diff: &VersionDiff) -> MigrationComplexity {
-        let mut complexity_score = 0;
-
-        // Schema changes add complexity
-        complexity_score += version_diff.schema_changes.len() * 2;
-
-        // Breaking changes add significant complexity
-        complexity_score += version_diff.breaking_changes.len() * 5;
-
-        // Data transformations add complexity
-        complexity_score += version_diff.data_transformations.len() * 3;
-
-        match complexity_score {
-            0..=5 => MigrationComplexity::Low,
-            6..=15 => MigrationComplexity::Medium,
-            16..=30 => MigrationComplexity::High,
-            _ => MigrationComplexity::Critical,
+    /// The drained version is sunset unless it has become the default again
+    async fn finish_drain(&self, rollout: &mut VersionRollout) -> WorkflowServiceResult<()> {
+        let from = self.require_version(&rollout.workflow_type, &rollout.from_version).await?;
+        if !from.is_default {
+            self.repository
+                .set_status(&rollout.workflow_type, &rollout.from_version, VersionStatus::Sunset)
+                .await?;
         }
+        rollout.status = MigrationStatus::Completed;
+        rollout.completed_at = Some(Utc::now());
+        info!(
+            "Version {} of {} is drained",
+            rollout.from_version, rollout.workflow_type
+        );
+        Ok(())
     }
 
-    fn estimate_migration_duration(&self, request: &MigrateWorkflowsRequest, complexity: &MigrationComplexity) -> std::time::Duration {
-        let base_duration = match complexity {
-            MigrationComplexity::Low => std::time::Duration::from_secs(300),      // 5 minutes
-            MigrationComplexity::Medium => std::time::Duration::from_secs(900),   // 15 minutes
-            MigrationComplexity::High => std::time::Duration::from_secs(1800),    // 30 minutes
-            MigrationComplexity::Critical => std::time::Duration::from_secs(3600), // 1 hour
-        };
-
-        // Adjust based on number of workflows (assuming 100 workflows per minute)
-        let workflow_factor = (request.workflow_ids.as_ref().map(|ids| ids.len()).unwrap_or(1000) as f64 / 100.0).ceil() as u64;
-        
-        base_duration + std::time::Duration::from_secs(workflow_factor * 60)
-    }
-}
-
-/// Version registry for managing workflow versions
-pub struct VersionRegistry {
-    // In a real implementation, this would connect to a database
-}
-
-impl VersionRegistry {
-    pub fn new() -> Self {
-        Self {}
-    }
-
-    pub async fn register_version(&self, request: &RegisterVersionRequest, compatibility: CompatibilityInfo) -> WorkflowServiceResult<VersionRegistration> {
-        // Mock implementation
-        Ok(VersionRegistration {
-            success: true,
-            registered_at: Utc::now(),
-            compatibility_info: compatibility,
-            migration_required: false,
-            breaking_changes: vec![],
-        })
-    }
-
-    pub async fn get_versions(&self, workflow_type: &str) -> WorkflowServiceResult<Vec<WorkflowVersionInfo>> {
-        // Mock implementation
-        Ok(vec![
-            WorkflowVersionInfo {
-                version: "1.0.0".to_string(),
-                status: VersionStatus::Active,
-                registered_at: Utc::now() - chrono::Duration::days(30),
-                deprecated_at: None,
-                sunset_date: None,
-                active_workflows: 150,
-                description: "Initial version".to_string(),
-                breaking_changes: vec![],
-            },
-            WorkflowVersionInfo {
-                version: "1.1.0".to_string(),
-                status: VersionStatus::Active,
-                registered_at: Utc::now() - chrono::Duration::days(15),
-                deprecated_at: None,
-                sunset_date: None,
-                active_workflows: 75,
-                description: "Added retry improvements".to_string(),
-                breaking_changes: vec![],
-            },
-            WorkflowVersionInfo {
-                version: "2.0.0".to_string(),
-                status: VersionStatus::Beta,
-                registered_at: Utc::now() - chrono::Duration::days(5),
-                deprecated_at: None,
-                sunset_date: None,
-                active_workflows: 10,
-                description: "Major refactor with new activity structure".to_string(),
-                breaking_changes: vec![
-                    "Activity signatures changed".to_string(),
-                    "Workflow input format updated".to_string(),
-                ],
-            },
-        ])
-    }
-
-    pub async fn get_current_version(&self, workflow_type: &str) -> WorkflowServiceResult<String> {
-        Ok("1.1.0".to_string())
-    }
-
-    pub async fn get_deprecated_versions(&self, workflow_type: &str) -> WorkflowServiceResult<Vec<String>> {
-        Ok(vec![])
+    /// Every run still running on the version after checking with Temporal
+    async fn reconcile_all(
+        &self,
+        workflow_type: &str,
+        version: &str,
+        workflow_ids: Option<&[String]>,
+    ) -> WorkflowServiceResult<Vec<VersionedExecution>> {
+        let reconciled = self
+            .reconcile_running(workflow_type, version, workflow_ids, u32::MAX)
+            .await?;
+        Ok(reconciled.running)
     }
 
-    pub async fn compare_versions(&self, workflow_type: &str, from_version: &str, to_version: &str) -> WorkflowServiceResult<VersionDiff> {
-        // Mock implementation
-        Ok(VersionDiff {
-            from_version: from_version.to_string(),
-            to_version: to_version.to_string(),
-            schema_changes: vec![
-                SchemaChange {
-                    field: "user_data.email".to_string(),
-                    change_type: SchemaChangeType::FieldAdded,
-                    description: "Added email validation".to_string(),
-                    breaking: false,
-                },
-            ],
-            breaking_changes: vec![],
-            data_transformations: vec![],
-            activity_changes: vec![
-                ActivityChange {
-                    activity_name: "validate_user".to_string(),
-                    change_type: ActivityChangeType::SignatureChanged,
-                    description: "Added email parameter".to_string(),
-                    breaking: false,
-                },
-            ],
-        })
-    }
-
-    pub async fn deprecate_version(&self, request: &DeprecateVersionRequest) -> WorkflowServiceResult<DeprecationResult> {
-        Ok(DeprecationResult {
-            success: true,
-            deprecated_at: Utc::now(),
-            affected_workflows: 25,
-        })
+    /// Check up to `limit` runs recorded as running on the version against
+    /// Temporal and close the ones that have finished. Runs whose history
+    /// can't be read are kept as running.
+    async fn reconcile_running(
+        &self,
+        workflow_type: &str,
+        version: &str,
+        workflow_ids: Option<&[String]>,
+        limit: u32,
+    ) -> WorkflowServiceResult<ReconciledRuns> {
+        let candidates = self
+            .repository
+            .running_executions(workflow_type, version, workflow_ids, limit)
+            .await?;
+
+        let mut reconciled = ReconciledRuns::default();
+        for execution in candidates {
+            match self
+                .temporal
+                .get_workflow_history(&execution.workflow_id, Some(&execution.run_id))
+                .await
+            {
+                Ok(events) => {
+                    if let Some(status) = closed_status(&events) {
+                        let closed_at = events.last().map(|event| event.event_time).unwrap_or_else(Utc::now);
+                        self.repository
+                            .close_execution(&execution.workflow_id, &execution.run_id, execution_status(status), closed_at)
+                            .await?;
+                        continue;
+                    }
+                }
+                Err(error) => {
+                    warn!("Could not check run {} of {}: {}", execution.run_id, execution.workflow_id, error);
+                    reconciled.unverified += 1;
+                }
+            }
+            reconciled.running.push(execution);
+        }
+        Ok(reconciled)
     }
 }
 
-/// Migration execution engine
-pub struct MigrationEngine {
-    // In a real implementation, this would manage migration execution
+fn parse_version(version: &str) -> WorkflowServiceResult<WorkflowVersion> {
+    WorkflowVersion::parse(version.trim())
+        .map_err(|_| WorkflowServiceError::InvalidVersion(format!(
+            "Version must be in format 'major.minor.patch', got: {}",
+            version
+        )))
 }
 
-impl MigrationEngine {
-    pub fn new() -> Self {
-        Self {}
-    }
-
-    pub async fn execute_migration(&self, plan: &MigrationPlan) -> WorkflowServiceResult<MigrationResult> {
-        info!("Executing migration plan: {}", plan.migration_id);
-
-        // Mock implementation
-        Ok(MigrationResult {
-            migration_id: plan.migration_id.clone(),
-            total_workflows: 100,
-            migrated_workflows: 95,
-            failed_migrations: 5,
-            status: MigrationStatus::Completed,
-            started_at: Utc::now() - chrono::Duration::minutes(30),
-            completed_at: Some(Utc::now()),
-            errors: vec![
-                "Failed to migrate workflow_123: schema validation error".to_string(),
-            ],
-        })
-    }
-
-    pub async fn get_migration_status(&self, migration_id: &str) -> WorkflowServiceResult<MigrationStatusInfo> {
-        Ok(MigrationStatusInfo {
-            status: MigrationStatus::Completed,
-            progress: 95.0,
-            total_workflows: 100,
-            processed_workflows: 100,
-            successful_migrations: 95,
-            failed_migrations: 5,
-            current_batch: 10,
-            estimated_completion: None,
-            errors: vec![],
-            started_at: Utc::now() - chrono::Duration::minutes(30),
-            updated_at: Utc::now(),
-        })
-    }
-
-    pub async fn rollback_migration(&self, request: &RollbackMigrationRequest) -> WorkflowServiceResult<RollbackResult> {
-        Ok(RollbackResult {
-            rollback_id: Uuid::new_v4().to_string(),
-            success: true,
-            started_at: Utc::now(),
-            completed_at: Some(Utc::now() + chrono::Duration::minutes(15)),
-            workflows_rolled_back: 95,
-            message: "Migration rollback completed successfully".to_string(),
-        })
+fn compare_versions(a: &WorkflowVersion, b: &WorkflowVersion) -> Ordering {
+    if a.is_newer_than(b) {
+        Ordering::Greater
+    } else if b.is_newer_than(a) {
+        Ordering::Less
+    } else {
+        Ordering::Equal
     }
 }
 
-/// Compatibility checking service
-pub struct CompatibilityChecker {
-    // In a real implementation, this would analyze compatibility
-}
-
-impl CompatibilityChecker {
-    pub fn new() -> Self {
-        Self {}
-    }
-
-    pub async fn check_compatibility(&self, request: &RegisterVersionRequest) -> WorkflowServiceResult<CompatibilityInfo> {
-        Ok(CompatibilityInfo {
+/// Compatibility of a new version with the current default
+fn check_compatibility(
+    current: Option<&WorkflowVersionRecord>,
+    version: &WorkflowVersion,
+    breaking_changes: &[String],
+) -> WorkflowServiceResult<CompatibilityInfo> {
+    let Some(current) = current else {
+        return Ok(CompatibilityInfo {
             is_compatible: true,
             compatibility_level: CompatibilityLevel::Backward,
-            breaking_changes: vec![],
-            warnings: vec![],
+            breaking_changes: Vec::new(),
+            warnings: Vec::new(),
             migration_required: false,
-        })
-    }
+        });
+    };
 
-    pub async fn get_compatibility_matrix(&self, workflow_type: &str) -> WorkflowServiceResult<CompatibilityMatrix> {
-        Ok(CompatibilityMatrix {
-            compatibility_matrix: vec![
-                ("1.0.0".to_string(), "1.1.0".to_string(), CompatibilityLevel::Backward),
-                ("1.1.0".to_string(), "2.0.0".to_string(), CompatibilityLevel::None),
-            ],
-            breaking_changes: HashMap::new(),
-            migration_paths: vec![
-                MigrationPath {
-                    from_version: "1.0.0".to_string(),
-                    to_version: "2.0.0".to_string(),
-                    direct_migration: false,
-                    intermediate_versions: vec!["1.1.0".to_string()],
-                    complexity: MigrationComplexity::High,
-                },
-            ],
-            recommendations: vec![
-                "Migrate to 1.1.0 before upgrading to 2.0.0".to_string(),
-            ],
-        })
+    let current_version = parse_version(&current.version)?;
+    let mut warnings = Vec::new();
+    let same_major = version.major == current_version.major;
+    if !version.is_newer_than(&current_version) {
+        warnings.push(format!("Version {} is not newer than the default {}", version, current.version));
+    }
+    if same_major && !breaking_changes.is_empty() {
+        warnings.push("Breaking changes normally bump the major version".to_string());
     }
+
+    let compatibility_level = match (same_major, version.major > current_version.major) {
+        (true, _) if version.is_newer_than(&current_version) => CompatibilityLevel::Backward,
+        (true, _) => CompatibilityLevel::Forward,
+        _ => CompatibilityLevel::None,
+    };
+    let is_compatible = compatibility_level != CompatibilityLevel::None;
+
+    Ok(CompatibilityInfo {
+        is_compatible,
+        compatibility_level,
+        breaking_changes: breaking_changes.to_vec(),
+        warnings,
+        migration_required: !is_compatible,
+    })
+}
+
+#[derive(Debug, Default)]
+struct ReconciledRuns {
+    running: Vec<VersionedExecution>,
+    unverified: u32,
 }
 
 // Data structures for versioning
@@ -516,6 +737,11 @@ pub struct RegisterVersionRequest {
     pub schema: serde_json::Value,
     pub breaking_changes: Vec<String>,
     pub migration_notes: Option<String>,
+    /// Make this the version new runs start on
+    #[serde(default)]
+    pub make_default: bool,
+    #[serde(default)]
+    pub beta: bool,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
@@ -524,6 +750,7 @@ pub struct RegisterVersionResponse {
     pub version: String,
     pub registered: bool,
     pub registered_at: DateTime<Utc>,
+    pub is_default: bool,
     pub compatibility_info: CompatibilityInfo,
     pub migration_required: bool,
     pub breaking_changes: Vec<String>,
@@ -533,7 +760,7 @@ pub struct RegisterVersionResponse {
 pub struct WorkflowVersionsResponse {
     pub workflow_type: String,
     pub versions: Vec<WorkflowVersionInfo>,
-    pub current_version: String,
+    pub current_version: Option<String>,
     pub deprecated_versions: Vec<String>,
 }
 
@@ -541,65 +768,226 @@ pub struct WorkflowVersionsResponse {
 pub struct WorkflowVersionInfo {
     pub version: String,
     pub status: VersionStatus,
+    pub is_default: bool,
     pub registered_at: DateTime<Utc>,
     pub deprecated_at: Option<DateTime<Utc>>,
     pub sunset_date: Option<DateTime<Utc>>,
+    pub migration_deadline: Option<DateTime<Utc>>,
+    /// Runs recorded as running on this version
     pub active_workflows: u32,
     pub description: String,
     pub breaking_changes: Vec<String>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+/// A registered version as stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct WorkflowVersionRecord {
+    pub workflow_type: String,
+    pub version: String,
+    pub status: VersionStatus,
+    pub is_default: bool,
+    pub description: String,
+    pub schema: serde_json::Value,
+    pub breaking_changes: Vec<String>,
+    pub migration_notes: Option<String>,
+    pub registered_at: DateTime<Utc>,
+    pub deprecated_at: Option<DateTime<Utc>>,
+    pub deprecation_reason: Option<String>,
+    pub sunset_date: Option<DateTime<Utc>>,
+    pub migration_deadline: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub enum VersionStatus {
-    Active,
     Beta,
+    Active,
     Deprecated,
+    /// Deprecated and no longer running anywhere
     Sunset,
 }
 
+impl VersionStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            VersionStatus::Beta => "beta",
+            VersionStatus::Active => "active",
+            VersionStatus::Deprecated => "deprecated",
+            VersionStatus::Sunset => "sunset",
+        }
+    }
+
+    pub fn parse(value: &str) -> WorkflowServiceResult<Self> {
+        match value {
+            "beta" => Ok(VersionStatus::Beta),
+            "active" => Ok(VersionStatus::Active),
+            "deprecated" => Ok(VersionStatus::Deprecated),
+            "sunset" => Ok(VersionStatus::Sunset),
+            other => Err(WorkflowServiceError::Internal(format!("Unknown version status: {}", other))),
+        }
+    }
+}
+
+/// A run recorded in `workflow_executions` with the version it runs on
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionedExecution {
+    pub tenant_id: String,
+    pub workflow_id: String,
+    pub run_id: String,
+    pub version: String,
+    pub started_at: DateTime<Utc>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OutdatedExecutionsParams {
+    pub limit: Option<u32>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OutdatedExecutionsResponse {
+    pub workflow_type: String,
+    pub default_version: String,
+    pub versions: Vec<OutdatedVersion>,
+    /// Up to `limit` of the runs, oldest first
+    pub executions: Vec<VersionedExecution>,
+    /// Runs whose state Temporal could not confirm; they count as running
+    pub unverified: u32,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct OutdatedVersion {
+    pub version: String,
+    pub status: VersionStatus,
+    pub running: u32,
+    pub migration_deadline: Option<DateTime<Utc>>,
+    pub past_deadline: bool,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub enum RolloutStrategy {
+    /// Move running executions onto the new version
+    Patch,
+    /// Let running executions finish on the old version
+    Drain,
+}
+
+impl RolloutStrategy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            RolloutStrategy::Patch => "patch",
+            RolloutStrategy::Drain => "drain",
+        }
+    }
+
+    pub fn parse(value: &str) -> WorkflowServiceResult<Self> {
+        match value {
+            "patch" => Ok(RolloutStrategy::Patch),
+            "drain" => Ok(RolloutStrategy::Drain),
+            other => Err(WorkflowServiceError::Internal(format!("Unknown rollout strategy: {}", other))),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MigrateWorkflowsRequest {
     pub workflow_type: String,
     pub from_version: String,
     pub to_version: String,
-    pub workflow_ids: Option<Vec<String>>, // If None, migrate all
+    /// Patch rollouts only; if None, every run on `from_version` moves
+    pub workflow_ids: Option<Vec<String>>,
+    /// Defaults to patch within a major version and drain across them
+    pub strategy: Option<RolloutStrategy>,
     pub batch_size: Option<u32>,
     pub dry_run: Option<bool>,
     pub enable_rollback: Option<bool>,
 }
 
+/// A rollout as stored
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct VersionRollout {
+    pub migration_id: String,
+    pub workflow_type: String,
+    pub from_version: String,
+    pub to_version: String,
+    pub strategy: RolloutStrategy,
+    pub status: MigrationStatus,
+    pub dry_run: bool,
+    pub rollback_enabled: bool,
+    pub batch_size: u32,
+    /// Runs on the old version when the rollout started
+    pub total_workflows: u32,
+    /// Runs a patch rollout moved, so a rollback can move them back
+    pub migrated_workflow_ids: Vec<String>,
+    pub failed_migrations: u32,
+    /// Runs a drain is still waiting for
+    pub remaining_workflows: u32,
+    pub errors: Vec<String>,
+    pub started_at: DateTime<Utc>,
+    pub updated_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
+    pub rollback_reason: Option<String>,
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MigrateWorkflowsResponse {
     pub migration_id: String,
     pub workflow_type: String,
     pub from_version: String,
     pub to_version: String,
+    pub strategy: RolloutStrategy,
+    pub dry_run: bool,
     pub total_workflows: u32,
     pub migrated_workflows: u32,
     pub failed_migrations: u32,
+    pub remaining_workflows: u32,
     pub migration_status: MigrationStatus,
     pub started_at: DateTime<Utc>,
     pub completed_at: Option<DateTime<Utc>>,
     pub errors: Vec<String>,
 }
 
+impl From<VersionRollout> for MigrateWorkflowsResponse {
+    fn from(rollout: VersionRollout) -> Self {
+        Self {
+            migrated_workflows: rollout.migrated_workflow_ids.len() as u32,
+            migration_id: rollout.migration_id,
+            workflow_type: rollout.workflow_type,
+            from_version: rollout.from_version,
+            to_version: rollout.to_version,
+            strategy: rollout.strategy,
+            dry_run: rollout.dry_run,
+            total_workflows: rollout.total_workflows,
+            failed_migrations: rollout.failed_migrations,
+            remaining_workflows: rollout.remaining_workflows,
+            migration_status: rollout.status,
+            started_at: rollout.started_at,
+            completed_at: rollout.completed_at,
+            errors: rollout.errors,
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MigrationStatusResponse {
     pub migration_id: String,
+    pub workflow_type: String,
+    pub from_version: String,
+    pub to_version: String,
+    pub strategy: RolloutStrategy,
     pub status: MigrationStatus,
+    pub dry_run: bool,
     pub progress: f64,
     pub total_workflows: u32,
     pub processed_workflows: u32,
     pub successful_migrations: u32,
     pub failed_migrations: u32,
-    pub current_batch: u32,
-    pub estimated_completion: Option<DateTime<Utc>>,
+    pub remaining_workflows: u32,
     pub errors: Vec<String>,
     pub started_at: DateTime<Utc>,
     pub updated_at: DateTime<Utc>,
+    pub completed_at: Option<DateTime<Utc>>,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub enum MigrationStatus {
     Pending,
     Running,
@@ -608,6 +996,29 @@ pub enum MigrationStatus {
     RolledBack,
 }
 
+impl MigrationStatus {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            MigrationStatus::Pending => "pending",
+            MigrationStatus::Running => "running",
+            MigrationStatus::Completed => "completed",
+            MigrationStatus::Failed => "failed",
+            MigrationStatus::RolledBack => "rolled_back",
+        }
+    }
+
+    pub fn parse(value: &str) -> WorkflowServiceResult<Self> {
+        match value {
+            "pending" => Ok(MigrationStatus::Pending),
+            "running" => Ok(MigrationStatus::Running),
+            "completed" => Ok(MigrationStatus::Completed),
+            "failed" => Ok(MigrationStatus::Failed),
+            "rolled_back" => Ok(MigrationStatus::RolledBack),
+            other => Err(WorkflowServiceError::Internal(format!("Unknown migration status: {}", other))),
+        }
+    }
+}
+
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RollbackMigrationRequest {
     pub migration_id: String,
@@ -617,7 +1028,6 @@ pub struct RollbackMigrationRequest {
 #[derive(Debug, Serialize, Deserialize)]
 pub struct RollbackMigrationResponse {
     pub migration_id: String,
-    pub rollback_id: String,
     pub rolled_back: bool,
     pub rollback_started_at: DateTime<Utc>,
     pub rollback_completed_at: Option<DateTime<Utc>>,
@@ -642,20 +1052,20 @@ pub struct DeprecateVersionResponse {
     pub deprecated_at: DateTime<Utc>,
     pub sunset_date: Option<DateTime<Utc>>,
     pub migration_deadline: Option<DateTime<Utc>>,
+    /// Runs still on the version
     pub affected_workflows: u32,
 }
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CompatibilityMatrixResponse {
     pub workflow_type: String,
+    /// Consecutive versions, oldest first
     pub compatibility_matrix: Vec<(String, String, CompatibilityLevel)>,
     pub breaking_changes: HashMap<String, Vec<String>>,
     pub migration_paths: Vec<MigrationPath>,
     pub recommendations: Vec<String>,
 }
 
-// Internal data structures
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct CompatibilityInfo {
     pub is_compatible: bool,
@@ -665,94 +1075,13 @@ pub struct CompatibilityInfo {
     pub migration_required: bool,
 }
 
-#[derive(Debug, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
 pub enum CompatibilityLevel {
     Backward,    // Fully backward compatible
     Forward,     // Forward compatible only
     None,        // No compatibility
 }
 
-#[derive(Debug, Serialize, Deserialize)]
-pub struct VersionDiff {
-    pub from_version: String,
-    pub to_version: String,
-    pub schema_changes: Vec<SchemaChange>,
-    pub breaking_changes: Vec<String>,
-    pub data_transformations: Vec<DataTransformation>,
-    pub activity_changes: Vec<ActivityChange>,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct SchemaChange {
-    pub field: String,
-    pub change_type: SchemaChangeType,
-    pub description: String,
-    pub breaking: bool,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub enum SchemaChangeType {
-    FieldAdded,
-    FieldRemoved,
-    FieldTypeChanged,
-    FieldRenamed,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct DataTransformation {
-    pub field: String,
-    pub transformation: String,
-    pub description: String,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub struct ActivityChange {
-    pub activity_name: String,
-    pub change_type: ActivityChangeType,
-    pub description: String,
-    pub breaking: bool,
-}
-
-#[derive(Debug, Serialize, Deserialize)]
-pub enum ActivityChangeType {
-    SignatureChanged,
-    ActivityAdded,
-    ActivityRemoved,
-    ActivityRenamed,
-}
-
-#[derive(Debug)]
-pub struct MigrationPlan {
-    pub migration_id: String,
-    pub workflow_type: String,
-    pub from_version: String,
-    pub to_version: String,
-    pub migration_steps: Vec<MigrationStep>,
-    pub complexity: MigrationComplexity,
-    pub estimated_duration: std::time::Duration,
-    pub batch_size: u32,
-    pub dry_run: bool,
-    pub rollback_enabled: bool,
-}
-
-#[derive(Debug)]
-pub struct MigrationStep {
-    pub step_id: String,
-    pub step_type: MigrationStepType,
-    pub description: String,
-    pub required: bool,
-    pub rollback_supported: bool,
-    pub estimated_duration: std::time::Duration,
-}
-
-#[derive(Debug)]
-pub enum MigrationStepType {
-    SchemaUpdate,
-    DataTransformation,
-    ActivityMapping,
-    Validation,
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 pub enum MigrationComplexity {
     Low,
@@ -761,67 +1090,6 @@ pub enum MigrationComplexity {
     Critical,
 }
 
-#[derive(Debug)]
-pub struct VersionRegistration {
-    pub success: bool,
-    pub registered_at: DateTime<Utc>,
-    pub compatibility_info: CompatibilityInfo,
-    pub migration_required: bool,
-    pub breaking_changes: Vec<String>,
-}
-
-#[derive(Debug)]
-pub struct MigrationResult {
-    pub migration_id: String,
-    pub total_workflows: u32,
-    pub migrated_workflows: u32,
-    pub failed_migrations: u32,
-    pub status: MigrationStatus,
-    pub started_at: DateTime<Utc>,
-    pub completed_at: Option<DateTime<Utc>>,
-    pub errors: Vec<String>,
-}
-
-#[derive(Debug)]
-pub struct MigrationStatusInfo {
-    pub status: MigrationStatus,
-    pub progress: f64,
-    pub total_workflows: u32,
-    pub processed_workflows: u32,
-    pub successful_migrations: u32,
-    pub failed_migrations: u32,
-    pub current_batch: u32,
-    pub estimated_completion: Option<DateTime<Utc>>,
-    pub errors: Vec<String>,
-    pub started_at: DateTime<Utc>,
-    pub updated_at: DateTime<Utc>,
-}
-
-#[derive(Debug)]
-pub struct RollbackResult {
-    pub rollback_id: String,
-    pub success: bool,
-    pub started_at: DateTime<Utc>,
-    pub completed_at: Option<DateTime<Utc>>,
-    pub workflows_rolled_back: u32,
-    pub message: String,
-}
-
-#[derive(Debug)]
-pub struct DeprecationResult {
-    pub success: bool,
-    pub deprecated_at: DateTime<Utc>,
-    pub affected_workflows: u32,
-}
-
-#[derive(Debug)]
-pub struct CompatibilityMatrix {
-    pub compatibility_matrix: Vec<(String, String, CompatibilityLevel)>,
-    pub breaking_changes: HashMap<String, Vec<String>>,
-    pub migration_paths: Vec<MigrationPath>,
-    pub recommendations: Vec<String>,
-}
-
 #[derive(Debug, Serialize, Deserialize)]
 pub struct MigrationPath {
     pub from_version: String,
@@ -829,4 +1097,4 @@ pub struct MigrationPath {
     pub direct_migration: bool,
     pub intermediate_versions: Vec<String>,
     pub complexity: MigrationComplexity,
-}
\ No newline at end of file
+}
@@ -0,0 +1,461 @@
+use crate::error::{WorkflowServiceError, WorkflowServiceResult};
+use adx_shared::temporal::{decode_payloads, AdxTemporalClient, HistoryEvent, TemporalError};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use tracing::info;
+
+/// Bumped whenever the export document changes shape
+pub const HISTORY_EXPORT_FORMAT_VERSION: u32 = 1;
+
+/// Input fields that name the tenant a workflow runs for. Workflows that
+/// move data between tenants carry more than one.
+pub(crate) const TENANT_INPUT_FIELDS: [&str; 5] = [
+    "tenant_id",
+    "current_tenant_id",
+    "target_tenant_id",
+    "source_tenant_id",
+    "new_tenant_id",
+];
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum RunStatus {
+    Running,
+    Completed,
+    Failed,
+    Canceled,
+    Terminated,
+    TimedOut,
+    ContinuedAsNew,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeKind {
+    Activity,
+    ChildWorkflow,
+    Timer,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "snake_case")]
+pub enum NodeStatus {
+    Scheduled,
+    Running,
+    Completed,
+    Failed,
+    Canceled,
+    TimedOut,
+    Terminated,
+}
+
+impl NodeStatus {
+    fn is_closed(self) -> bool {
+        !matches!(self, NodeStatus::Scheduled | NodeStatus::Running)
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ChildWorkflowRef {
+    pub workflow_id: String,
+    pub run_id: Option<String>,
+}
+
+/// An activity, child workflow or timer in a run, with its timings
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GraphNode {
+    /// Stable within a run: the kind plus the id of the event that created it
+    pub node_id: String,
+    pub kind: NodeKind,
+    /// Activity type, child workflow type or timer id
+    pub name: String,
+    pub status: NodeStatus,
+    pub scheduled_at: DateTime<Utc>,
+    pub started_at: Option<DateTime<Utc>>,
+    pub closed_at: Option<DateTime<Utc>>,
+    /// Time spent waiting for a worker to pick the task up
+    pub queued_ms: Option<i64>,
+    /// Time from start (or scheduling, for timers) until close
+    pub duration_ms: Option<i64>,
+    /// Activity attempts, including the last one. Always 1 for other kinds.
+    pub attempts: u32,
+    pub failure: Option<String>,
+    pub child_workflow: Option<ChildWorkflowRef>,
+}
+
+/// `from` closed before the workflow code scheduled `to`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct GraphEdge {
+    pub from: String,
+    pub to: String,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SignalMarker {
+    pub name: String,
+    pub received_at: DateTime<Utc>,
+}
+
+/// A workflow run reduced to what a Gantt or graph view needs
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionGraph {
+    pub workflow_id: String,
+    pub run_id: Option<String>,
+    pub workflow_type: Option<String>,
+    pub task_queue: Option<String>,
+    pub status: RunStatus,
+    pub started_at: Option<DateTime<Utc>>,
+    pub closed_at: Option<DateTime<Utc>>,
+    pub duration_ms: Option<i64>,
+    /// Set when the run continued as new, so the UI can follow the chain
+    pub next_run_id: Option<String>,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<GraphEdge>,
+    pub signals: Vec<SignalMarker>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExecutionHistoryExport {
+    pub format_version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub graph: ExecutionGraph,
+    pub events: Vec<HistoryEvent>,
+}
+
+#[derive(Debug, Deserialize)]
+pub struct ExecutionGraphParams {
+    pub run_id: Option<String>,
+}
+
+impl ExecutionGraph {
+    /// Build the graph from a run's history. Events must be in history order.
+    pub fn from_history(workflow_id: &str, events: &[HistoryEvent]) -> Self {
+        let mut graph = ExecutionGraph {
+            workflow_id: workflow_id.to_string(),
+            run_id: None,
+            workflow_type: None,
+            task_queue: None,
+            status: RunStatus::Running,
+            started_at: None,
+            closed_at: None,
+            duration_ms: None,
+            next_run_id: None,
+            nodes: Vec::new(),
+            edges: Vec::new(),
+            signals: Vec::new(),
+        };
+
+        // Node index by the id of the event that created it
+        let mut nodes_by_event: HashMap<i64, usize> = HashMap::new();
+        // Nodes that closed since the last workflow task completed; whatever
+        // that task goes on to schedule was decided after seeing them
+        let mut closed_since_task: Vec<String> = Vec::new();
+        let mut predecessors: HashMap<i64, Vec<String>> = HashMap::new();
+
+        for event in events {
+            let attrs = &event.attributes;
+            match event.event_type.as_str() {
+                "WorkflowExecutionStarted" => {
+                    graph.started_at = Some(event.event_time);
+                    graph.run_id = event.attribute_str("originalExecutionRunId").map(str::to_string);
+                    graph.workflow_type = nested_str(attrs, &["workflowType", "name"]);
+                    graph.task_queue = nested_str(attrs, &["taskQueue", "name"]);
+                }
+                "WorkflowTaskCompleted" => {
+                    predecessors.insert(event.event_id, std::mem::take(&mut closed_since_task));
+                }
+                "WorkflowExecutionSignaled" => {
+                    graph.signals.push(SignalMarker {
+                        name: event.attribute_str("signalName").unwrap_or_default().to_string(),
+                        received_at: event.event_time,
+                    });
+                }
+                "ActivityTaskScheduled" => {
+                    let name = nested_str(attrs, &["activityType", "name"]).unwrap_or_default();
+                    graph.add_node(&mut nodes_by_event, &predecessors, event, NodeKind::Activity, name);
+                }
+                "StartChildWorkflowExecutionInitiated" => {
+                    let name = nested_str(attrs, &["workflowType", "name"]).unwrap_or_default();
+                    let index = graph.add_node(&mut nodes_by_event, &predecessors, event, NodeKind::ChildWorkflow, name);
+                    graph.nodes[index].child_workflow = event.attribute_str("workflowId").map(|id| ChildWorkflowRef {
+                        workflow_id: id.to_string(),
+                        run_id: None,
+                    });
+                }
+                "TimerStarted" => {
+                    let name = event.attribute_str("timerId").unwrap_or_default().to_string();
+                    graph.add_node(&mut nodes_by_event, &predecessors, event, NodeKind::Timer, name);
+                }
+                "ActivityTaskStarted" => {
+                    if let Some(node) = graph.node_for(&nodes_by_event, event, "scheduledEventId") {
+                        node.status = NodeStatus::Running;
+                        node.started_at = Some(event.event_time);
+                        node.attempts = event.attribute_i64("attempt").unwrap_or(1).max(1) as u32;
+                    }
+                }
+                "ChildWorkflowExecutionStarted" => {
+                    if let Some(node) = graph.node_for(&nodes_by_event, event, "initiatedEventId") {
+                        node.status = NodeStatus::Running;
+                        node.started_at = Some(event.event_time);
+                        if let Some(child) = node.child_workflow.as_mut() {
+                            child.run_id = nested_str(attrs, &["workflowExecution", "runId"]);
+                        }
+                    }
+                }
+                event_type => {
+                    if let Some(status) = run_close_status(event_type) {
+                        graph.status = status;
+                        graph.closed_at = Some(event.event_time);
+                        graph.next_run_id = event.attribute_str("newExecutionRunId").map(str::to_string);
+                    } else if let Some((reference, status)) = node_close(event_type) {
+                        if let Some(node) = graph.node_for(&nodes_by_event, event, reference) {
+                            node.status = status;
+                            node.closed_at = Some(event.event_time);
+                            node.failure = failure_message(event);
+                            closed_since_task.push(node.node_id.clone());
+                        }
+                    }
+                }
+            }
+        }
+
+        for node in &mut graph.nodes {
+            node.queued_ms = node.started_at.map(|started| millis(node.scheduled_at, started));
+            node.duration_ms = node
+                .closed_at
+                .map(|closed| millis(node.started_at.unwrap_or(node.scheduled_at), closed));
+        }
+        graph.duration_ms = match (graph.started_at, graph.closed_at) {
+            (Some(started), Some(closed)) => Some(millis(started, closed)),
+            _ => None,
+        };
+
+        graph
+    }
+
+    fn add_node(
+        &mut self,
+        nodes_by_event: &mut HashMap<i64, usize>,
+        predecessors: &HashMap<i64, Vec<String>>,
+        event: &HistoryEvent,
+        kind: NodeKind,
+        name: String,
+    ) -> usize {
+        let prefix = match kind {
+            NodeKind::Activity => "activity",
+            NodeKind::ChildWorkflow => "child",
+            NodeKind::Timer => "timer",
+        };
+        let node_id = format!("{}-{}", prefix, event.event_id);
+
+        if let Some(from) = event
+            .attribute_i64("workflowTaskCompletedEventId")
+            .and_then(|task| predecessors.get(&task))
+        {
+            self.edges.extend(from.iter().map(|from| GraphEdge {
+                from: from.clone(),
+                to: node_id.clone(),
+            }));
+        }
+
+        self.nodes.push(GraphNode {
+            node_id,
+            kind,
+            name,
+            // Timers have no separate start event; they run once created
+            status: if kind == NodeKind::Timer { NodeStatus::Running } else { NodeStatus::Scheduled },
+            scheduled_at: event.event_time,
+            started_at: None,
+            closed_at: None,
+            queued_ms: None,
+            duration_ms: None,
+            attempts: 1,
+            failure: None,
+            child_workflow: None,
+        });
+        nodes_by_event.insert(event.event_id, self.nodes.len() - 1);
+        self.nodes.len() - 1
+    }
+
+    fn node_for(
+        &mut self,
+        nodes_by_event: &HashMap<i64, usize>,
+        event: &HistoryEvent,
+        reference: &str,
+    ) -> Option<&mut GraphNode> {
+        let index = event.attribute_i64(reference).and_then(|id| nodes_by_event.get(&id))?;
+        self.nodes.get_mut(*index).filter(|node| !node.status.is_closed())
+    }
+}
+
+fn run_close_status(event_type: &str) -> Option<RunStatus> {
+    match event_type {
+        "WorkflowExecutionCompleted" => Some(RunStatus::Completed),
+        "WorkflowExecutionFailed" => Some(RunStatus::Failed),
+        "WorkflowExecutionCanceled" => Some(RunStatus::Canceled),
+        "WorkflowExecutionTerminated" => Some(RunStatus::Terminated),
+        "WorkflowExecutionTimedOut" => Some(RunStatus::TimedOut),
+        "WorkflowExecutionContinuedAsNew" => Some(RunStatus::ContinuedAsNew),
+        _ => None,
+    }
+}
+
+/// Events that close a node, with the attribute pointing at the event that
+/// created it
+fn node_close(event_type: &str) -> Option<(&'static str, NodeStatus)> {
+    match event_type {
+        "ActivityTaskCompleted" => Some(("scheduledEventId", NodeStatus::Completed)),
+        "ActivityTaskFailed" => Some(("scheduledEventId", NodeStatus::Failed)),
+        "ActivityTaskTimedOut" => Some(("scheduledEventId", NodeStatus::TimedOut)),
+        "ActivityTaskCanceled" => Some(("scheduledEventId", NodeStatus::Canceled)),
+        "ChildWorkflowExecutionCompleted" => Some(("initiatedEventId", NodeStatus::Completed)),
+        "ChildWorkflowExecutionFailed" => Some(("initiatedEventId", NodeStatus::Failed)),
+        "ChildWorkflowExecutionTimedOut" => Some(("initiatedEventId", NodeStatus::TimedOut)),
+        "ChildWorkflowExecutionCanceled" => Some(("initiatedEventId", NodeStatus::Canceled)),
+        "ChildWorkflowExecutionTerminated" => Some(("initiatedEventId", NodeStatus::Terminated)),
+        "StartChildWorkflowExecutionFailed" => Some(("initiatedEventId", NodeStatus::Failed)),
+        "TimerFired" => Some(("startedEventId", NodeStatus::Completed)),
+        "TimerCanceled" => Some(("startedEventId", NodeStatus::Canceled)),
+        _ => None,
+    }
+}
+
+fn failure_message(event: &HistoryEvent) -> Option<String> {
+    if let Some(message) = nested_str(&event.attributes, &["failure", "message"]) {
+        return Some(message);
+    }
+    match event.event_type.as_str() {
+        "StartChildWorkflowExecutionFailed" => event.attribute_str("cause").map(str::to_string),
+        "ActivityTaskTimedOut" | "ChildWorkflowExecutionTimedOut" => Some("Timed out".to_string()),
+        _ => None,
+    }
+}
+
+fn nested_str(value: &serde_json::Value, path: &[&str]) -> Option<String> {
+    path.iter()
+        .try_fold(value, |value, key| value.get(key))
+        .and_then(|v| v.as_str())
+        .map(str::to_string)
+}
+
+fn millis(from: DateTime<Utc>, to: DateTime<Utc>) -> i64 {
+    (to - from).num_milliseconds().max(0)
+}
+
+/// Whether the run was started for the tenant, judged by its input
+fn started_for_tenant(events: &[HistoryEvent], tenant_id: &str) -> bool {
+    let Some(started) = events.iter().find(|e| e.event_type == "WorkflowExecutionStarted") else {
+        return false;
+    };
+    let Some(input) = started.attributes.get("input") else {
+        return false;
+    };
+
+    decode_payloads(input).iter().any(|payload| {
+        TENANT_INPUT_FIELDS
+            .iter()
+            .any(|field| payload.get(field).and_then(|v| v.as_str()) == Some(tenant_id))
+    })
+}
+
+/// The run's history, or not found when it belongs to another tenant
+pub(crate) async fn tenant_history(
+    temporal: &AdxTemporalClient,
+    tenant_id: &str,
+    workflow_id: &str,
+    run_id: Option<&str>,
+) -> WorkflowServiceResult<Vec<HistoryEvent>> {
+    let events = temporal
+        .get_workflow_history(workflow_id, run_id)
+        .await
+        .map_err(|e| match e {
+            TemporalError::WorkflowNotFound { .. } => {
+                WorkflowServiceError::NotFound(format!("Workflow {} not found", workflow_id))
+            }
+            other => WorkflowServiceError::Temporal(other.to_string()),
+        })?;
+
+    if !started_for_tenant(&events, tenant_id) {
+        return Err(WorkflowServiceError::NotFound(format!("Workflow {} not found", workflow_id)));
+    }
+
+    Ok(events)
+}
+
+/// The workflow type recorded when the run started
+pub(crate) fn started_workflow_type(events: &[HistoryEvent]) -> Option<String> {
+    events
+        .iter()
+        .find(|e| e.event_type == "WorkflowExecutionStarted")
+        .and_then(|started| nested_str(&started.attributes, &["workflowType", "name"]))
+}
+
+/// How the run closed, judged by its last event; None while it's running
+pub(crate) fn closed_status(events: &[HistoryEvent]) -> Option<RunStatus> {
+    events.last().and_then(|event| run_close_status(&event.event_type))
+}
+
+/// `workflow_executions` status for a run in this state
+pub(crate) fn execution_status(status: RunStatus) -> &'static str {
+    match status {
+        RunStatus::Running => "running",
+        RunStatus::Completed | RunStatus::ContinuedAsNew => "completed",
+        RunStatus::Failed => "failed",
+        RunStatus::Canceled => "cancelled",
+        RunStatus::Terminated => "terminated",
+        RunStatus::TimedOut => "timed_out",
+    }
+}
+
+/// Whether the run has closed, judged by its last event
+pub(crate) fn run_is_closed(events: &[HistoryEvent]) -> bool {
+    closed_status(events).is_some()
+}
+
+/// Reads run histories from Temporal for the visualization endpoints
+pub struct ExecutionHistoryManager {
+    temporal: AdxTemporalClient,
+}
+
+impl ExecutionHistoryManager {
+    pub fn new(temporal: AdxTemporalClient) -> Self {
+        Self { temporal }
+    }
+
+    pub async fn graph(
+        &self,
+        tenant_id: &str,
+        workflow_id: &str,
+        run_id: Option<&str>,
+    ) -> WorkflowServiceResult<ExecutionGraph> {
+        let events = self.history(tenant_id, workflow_id, run_id).await?;
+        Ok(ExecutionGraph::from_history(workflow_id, &events))
+    }
+
+    pub async fn export(
+        &self,
+        tenant_id: &str,
+        workflow_id: &str,
+        run_id: Option<&str>,
+    ) -> WorkflowServiceResult<ExecutionHistoryExport> {
+        let events = self.history(tenant_id, workflow_id, run_id).await?;
+
+        Ok(ExecutionHistoryExport {
+            format_version: HISTORY_EXPORT_FORMAT_VERSION,
+            exported_at: Utc::now(),
+            graph: ExecutionGraph::from_history(workflow_id, &events),
+            events,
+        })
+    }
+
+    async fn history(
+        &self,
+        tenant_id: &str,
+        workflow_id: &str,
+        run_id: Option<&str>,
+    ) -> WorkflowServiceResult<Vec<HistoryEvent>> {
+        info!(workflow_id = workflow_id, tenant_id = tenant_id, "Reading workflow execution history");
+        tenant_history(&self.temporal, tenant_id, workflow_id, run_id).await
+    }
+}
@@ -2,6 +2,8 @@ use crate::{
     activities::{CrossServiceActivities, CrossServiceActivitiesImpl},
     config::WorkflowServiceConfig,
     error::{WorkflowServiceError, WorkflowServiceResult},
+    templates::TEMPLATE_WORKFLOW_TYPE,
+    transactions::{business_transaction_workflow, BusinessTransactionRequest, BusinessTransactionResult, BUSINESS_TRANSACTION_WORKFLOW_TYPE},
     workflows::*,
 };
 use std::sync::Arc;
@@ -27,7 +29,14 @@ impl WorkflowWorker {
         info!("Starting Workflow Service Temporal worker");
         info!("Temporal server: {}", self.config.temporal.server_url);
         info!("Namespace: {}", self.config.temporal.namespace);
-        info!("Task queue: {}", self.config.temporal.task_queue);
+        let worker_config = self.config.worker_config();
+        for task_queue in &worker_config.task_queues {
+            let limits = worker_config.limits_for(task_queue);
+            info!(
+                "Task queue: {} (workflow slots: {}, activity slots: {})",
+                task_queue, limits.max_concurrent_workflow_tasks, limits.max_concurrent_activity_tasks
+            );
+        }
         info!("Worker identity: {}", self.config.temporal.worker_identity);
 
         // In a real implementation, this would:
@@ -53,6 +62,9 @@ impl WorkflowWorker {
         // - data_migration_workflow
         // - bulk_operation_workflow
         // - compliance_workflow
+        // - module_job_workflow
+        // - template_workflow (including its approval steps)
+        // - business_transaction_workflow
         
         info!("Registered workflows:");
         info!("  - user_onboarding_workflow");
@@ -60,6 +72,9 @@ impl WorkflowWorker {
         info!("  - data_migration_workflow");
         info!("  - bulk_operation_workflow");
         info!("  - compliance_workflow");
+        info!("  - module_job_workflow");
+        info!("  - {}", TEMPLATE_WORKFLOW_TYPE);
+        info!("  - {}", BUSINESS_TRANSACTION_WORKFLOW_TYPE);
         
         Ok(())
     }
@@ -74,6 +89,7 @@ impl WorkflowWorker {
         info!("  - validate_user_credentials");
         info!("  - update_user_session");
         info!("  - revoke_user_sessions");
+        info!("  - delete_user_account");
         
         // User Service Activities
         info!("Registered User Service activities:");
@@ -88,6 +104,8 @@ impl WorkflowWorker {
         info!("  - get_tenant_context");
         info!("  - update_tenant_user_membership");
         info!("  - get_tenant_data_for_migration");
+        info!("  - create_tenant");
+        info!("  - delete_tenant");
         
         // File Service Activities
         info!("Registered File Service activities:");
@@ -96,6 +114,15 @@ impl WorkflowWorker {
         info!("  - export_user_files");
         info!("  - delete_user_files");
         
+        // License Service Activities
+        info!("Registered License Service activities:");
+        info!("  - provision_license");
+        info!("  - revoke_license");
+        
+        // Module Service Activities
+        info!("Registered Module Service activities:");
+        info!("  - run_module_job");
+        
         // Cross-Service Coordination Activities
         info!("Registered Cross-Service Coordination activities:");
         info!("  - coordinate_service_health_check");
@@ -103,6 +130,13 @@ impl WorkflowWorker {
         info!("  - restore_from_backup");
         info!("  - send_notification");
         
+        // Human Approval Activities
+        info!("Registered Human Approval activities:");
+        info!("  - create_approval_task");
+        info!("  - get_approval_task");
+        info!("  - escalate_approval_task");
+        info!("  - expire_approval_task");
+        
         Ok(())
     }
 
@@ -211,6 +245,22 @@ pub async fn handle_compliance_workflow_task(
     compliance_workflow(input, activities.as_ref()).await
 }
 
+pub async fn handle_module_job_workflow_task(
+    input: crate::models::ModuleJobRequest,
+    activities: Arc<dyn CrossServiceActivities>,
+) -> WorkflowServiceResult<crate::models::ModuleJobResult> {
+    info!("Executing module job workflow task");
+    module_job_workflow(input, activities.as_ref()).await
+}
+
+pub async fn handle_business_transaction_workflow_task(
+    input: BusinessTransactionRequest,
+    activities: Arc<dyn CrossServiceActivities>,
+) -> WorkflowServiceResult<BusinessTransactionResult> {
+    info!("Executing business transaction workflow task");
+    business_transaction_workflow(input, activities.as_ref()).await
+}
+
 // Activity execution handlers
 // In a real implementation, these would be called by the Temporal SDK for each activity
 
@@ -280,6 +330,14 @@ pub fn get_workflow_registrations() -> Vec<WorkflowRegistration> {
             name: "compliance_workflow".to_string(),
             handler: "handle_compliance_workflow_task".to_string(),
         },
+        WorkflowRegistration {
+            name: "module_job_workflow".to_string(),
+            handler: "handle_module_job_workflow_task".to_string(),
+        },
+        WorkflowRegistration {
+            name: BUSINESS_TRANSACTION_WORKFLOW_TYPE.to_string(),
+            handler: "handle_business_transaction_workflow_task".to_string(),
+        },
     ]
 }
 
@@ -302,6 +360,10 @@ pub fn get_activity_registrations() -> Vec<ActivityRegistration> {
             name: "revoke_user_sessions".to_string(),
             handler: "handle_revoke_user_sessions_activity".to_string(),
         },
+        ActivityRegistration {
+            name: "delete_user_account".to_string(),
+            handler: "handle_delete_user_account_activity".to_string(),
+        },
         
         // User Service Activities
         ActivityRegistration {
@@ -338,6 +400,14 @@ pub fn get_activity_registrations() -> Vec<ActivityRegistration> {
             name: "get_tenant_data_for_migration".to_string(),
             handler: "handle_get_tenant_data_for_migration_activity".to_string(),
         },
+        ActivityRegistration {
+            name: "create_tenant".to_string(),
+            handler: "handle_create_tenant_activity".to_string(),
+        },
+        ActivityRegistration {
+            name: "delete_tenant".to_string(),
+            handler: "handle_delete_tenant_activity".to_string(),
+        },
         
         // File Service Activities
         ActivityRegistration {
@@ -357,6 +427,22 @@ pub fn get_activity_registrations() -> Vec<ActivityRegistration> {
             handler: "handle_delete_user_files_activity".to_string(),
         },
         
+        // License Service Activities
+        ActivityRegistration {
+            name: "provision_license".to_string(),
+            handler: "handle_provision_license_activity".to_string(),
+        },
+        ActivityRegistration {
+            name: "revoke_license".to_string(),
+            handler: "handle_revoke_license_activity".to_string(),
+        },
+        
+        // Module Service Activities
+        ActivityRegistration {
+            name: "run_module_job".to_string(),
+            handler: "handle_run_module_job_activity".to_string(),
+        },
+        
         // Cross-Service Coordination Activities
         ActivityRegistration {
             name: "coordinate_service_health_check".to_string(),
@@ -374,5 +460,23 @@ pub fn get_activity_registrations() -> Vec<ActivityRegistration> {
             name: "send_notification".to_string(),
             handler: "handle_send_notification_activity".to_string(),
         },
+        
+        // Human Approval Activities
+        ActivityRegistration {
+            name: "create_approval_task".to_string(),
+            handler: "handle_create_approval_task_activity".to_string(),
+        },
+        ActivityRegistration {
+            name: "get_approval_task".to_string(),
+            handler: "handle_get_approval_task_activity".to_string(),
+        },
+        ActivityRegistration {
+            name: "escalate_approval_task".to_string(),
+            handler: "handle_escalate_approval_task_activity".to_string(),
+        },
+        ActivityRegistration {
+            name: "expire_approval_task".to_string(),
+            handler: "handle_expire_approval_task_activity".to_string(),
+        },
     ]
 }
\ No newline at end of file
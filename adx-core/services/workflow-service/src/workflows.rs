@@ -233,6 +233,7 @@ pub async fn data_migration_workflow(
 
     // Step 3: Process data migration for each service
     for data_selector in &request.data_selectors {
+        activities.checkpoint().await?;
         services_affected.push(data_selector.service.clone());
         
         match data_selector.service.as_str() {
@@ -355,6 +356,7 @@ pub async fn bulk_operation_workflow(
     let batches: Vec<_> = request.target_entities.chunks(batch_size).collect();
 
     for (batch_index, batch) in batches.iter().enumerate() {
+        activities.checkpoint().await?;
         info!("Processing batch {} of {}", batch_index + 1, batches.len());
 
         // Process batch entities in parallel if configured
@@ -462,7 +464,7 @@ pub async fn compliance_workflow(
 
 // Helper functions for workflows
 
-fn get_default_permissions_for_role(role: &str) -> Vec<String> {
+pub(crate) fn get_default_permissions_for_role(role: &str) -> Vec<String> {
     match role {
         "admin" => vec![
             "tenant:read".to_string(),
@@ -723,4 +725,24 @@ impl std::fmt::Display for ComplianceType {
             ComplianceType::DataClassification => write!(f, "DATA_CLASSIFICATION"),
         }
     }
-}
\ No newline at end of file
+}
+
+// Module Job Workflow - Runs a module's background job on its schedule
+pub async fn module_job_workflow(
+    request: ModuleJobRequest,
+    activities: &dyn CrossServiceActivities,
+) -> WorkflowServiceResult<ModuleJobResult> {
+    info!("Starting module job workflow: {} of {} for tenant: {}",
+           request.job_name, request.module_id, request.tenant_id);
+
+    // Module-service records the run and its outcome where tenant admins
+    // see it; a failed run is not retried here, the next tick runs it again
+    let result = activities.run_module_job(request).await?;
+    match result.status.as_str() {
+        "succeeded" => info!("Module job run {} succeeded", result.run_id),
+        "skipped" => info!("Module job run {} skipped: {}", result.run_id, result.error.as_deref().unwrap_or_default()),
+        status => warn!("Module job run {} {}: {}", result.run_id, status, result.error.as_deref().unwrap_or_default()),
+    }
+
+    Ok(result)
+}
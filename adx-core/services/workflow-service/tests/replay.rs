@@ -0,0 +1,24 @@
+//! Replays every history under `tests/histories` against the current
+//! workflow code. A failure means the change would break runs started on
+//! the code the history was recorded from. Record histories from staging
+//! with `workflow-service record-history <workflow_id>`.
+
+use adx_shared::temporal::load_recorded_histories;
+use std::path::Path;
+use workflow_service::replay::replay_history;
+
+#[tokio::test]
+async fn recorded_histories_replay() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests").join("histories");
+    let histories = load_recorded_histories(&dir).expect("recorded histories should load");
+    assert!(!histories.is_empty(), "No recorded histories in {}", dir.display());
+
+    let mut failures = Vec::new();
+    for (path, history) in histories {
+        if let Err(error) = replay_history(history).await {
+            failures.push(format!("{}: {}", path.display(), error));
+        }
+    }
+
+    assert!(failures.is_empty(), "Histories that no longer replay:\n{}", failures.join("\n"));
+}